@@ -1,4 +1,90 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 fn main() {
     // 纭繚搴撳拰浜岃繘鍒剁▼搴忕殑閾炬帴姝ｇ‘
     println!("cargo:rerun-if-changed=src/lib.rs");
+
+    // Build metadata read by `src/build_info.rs`. Everything here is derived
+    // from the source tree and toolchain so two builds of the same commit
+    // report the same values.
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    let git_dir = Path::new(&manifest_dir).join(".git");
+    let mut watched = vec!["HEAD".to_string(), "index".to_string()];
+    // a commit moves the branch ref, not HEAD itself
+    if let Some(head_ref) = fs::read_to_string(git_dir.join("HEAD")).ok().and_then(|h| {
+        h.trim().strip_prefix("ref: ").map(str::to_string)
+    }) {
+        watched.push(head_ref);
+    }
+    for path in watched.iter().map(|w| git_dir.join(w)).filter(|p| p.exists()) {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    // container builds have no .git; they pass the commit in
+    let commit = git(&manifest_dir, &["rev-parse", "HEAD"])
+        .or_else(|| env::var("GIT_COMMIT").ok().filter(|c| !c.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string());
+    let dirty = git(&manifest_dir, &["status", "--porcelain", "--untracked-files=no"]).is_some_and(|s| !s.is_empty());
+    println!("cargo:rustc-env=BUILD_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=BUILD_GIT_DIRTY={}", dirty);
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=BUILD_RUSTC_VERSION={}", rustc_version);
+
+    // SOURCE_DATE_EPOCH, else the commit time, so the timestamp does not vary
+    // between rebuilds of one commit; wall clock only outside a checkout
+    let timestamp = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .or_else(|| git(&manifest_dir, &["log", "-1", "--format=%ct"]))
+        .and_then(|s| s.trim().parse::<i64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0));
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", timestamp);
+
+    println!("cargo:rustc-env=BUILD_FEATURES={}", enabled_features(&manifest_dir).join(","));
+}
+
+fn git(dir: &str, args: &[&str]) -> Option<String> {
+    let out = Command::new("git").arg("-C").arg(dir).args(args).output().ok()?;
+    out.status.success().then(|| String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+/// Features declared in `[features]` whose `CARGO_FEATURE_*` variable cargo
+/// set for this build, by their declared names, sorted.
+fn enabled_features(manifest_dir: &str) -> Vec<String> {
+    let manifest = fs::read_to_string(Path::new(manifest_dir).join("Cargo.toml")).unwrap_or_default();
+    let mut in_features = false;
+    let mut features = Vec::new();
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_features = line == "[features]";
+            continue;
+        }
+        if !in_features || line.starts_with('#') {
+            continue;
+        }
+        let Some((name, _)) = line.split_once('=') else {
+            continue;
+        };
+        let name = name.trim().trim_matches('"');
+        let var = format!("CARGO_FEATURE_{}", name.to_uppercase().replace('-', "_"));
+        if env::var_os(var).is_some() {
+            features.push(name.to_string());
+        }
+    }
+    features.sort();
+    features
 }
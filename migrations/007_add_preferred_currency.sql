@@ -0,0 +1,6 @@
+-- 用户偏好：法币估值的计价货币
+-- Migration: 007_add_preferred_currency
+-- Date: 2026-10-16
+
+-- NULL 表示使用服务端配置的默认货币（pricing.default_currency）
+ALTER TABLE user_preferences ADD COLUMN preferred_currency TEXT;
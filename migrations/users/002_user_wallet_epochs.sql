@@ -0,0 +1,25 @@
+-- 每个用户的钱包列表版本号，供 GET /api/wallets 的 ETag 使用
+-- 由触发器在修改 user_wallets 的同一事务内递增，任何写入路径都不会漏掉
+
+CREATE TABLE IF NOT EXISTS user_wallet_epochs (
+    user_id TEXT PRIMARY KEY,
+    epoch INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TRIGGER IF NOT EXISTS user_wallets_epoch_insert AFTER INSERT ON user_wallets
+BEGIN
+    INSERT OR IGNORE INTO user_wallet_epochs (user_id) VALUES (NEW.user_id);
+    UPDATE user_wallet_epochs SET epoch = epoch + 1 WHERE user_id = NEW.user_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS user_wallets_epoch_update AFTER UPDATE ON user_wallets
+BEGIN
+    INSERT OR IGNORE INTO user_wallet_epochs (user_id) VALUES (OLD.user_id), (NEW.user_id);
+    UPDATE user_wallet_epochs SET epoch = epoch + 1 WHERE user_id IN (OLD.user_id, NEW.user_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS user_wallets_epoch_delete AFTER DELETE ON user_wallets
+BEGIN
+    INSERT OR IGNORE INTO user_wallet_epochs (user_id) VALUES (OLD.user_id);
+    UPDATE user_wallet_epochs SET epoch = epoch + 1 WHERE user_id = OLD.user_id;
+END;
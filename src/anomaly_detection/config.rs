@@ -30,25 +30,30 @@ pub struct AnomalyDetectionConfig {
     
     /// 性能配置
     pub performance: PerformanceConfig,
+
+    /// wallet行为画像配置
+    #[serde(default)]
+    pub profiles: ProfileConfig,
+
+    /// 服务端sign发送：检测结果为 High 及以上的异常transaction以 403 拒绝；
+    /// 关闭时（默认）只把分数写入审计日志
+    #[serde(default)]
+    pub block_on_high: bool,
 }
 
 
 /// 检测模式配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum DetectionModeConfig {
     /// 阻止模式 - 阻止所有可疑transaction
     Block,
     /// Warning模式 - 仅记录Warning
+    #[default]
     Warn,
     /// 监控模式 - 收集数据但不干预
     Monitor,
 }
 
-impl Default for DetectionModeConfig {
-    fn default() -> Self {
-        Self::Warn
-    }
-}
 
 /// 特征提取配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -308,6 +313,49 @@ pub struct EventSubscriberConfig {
     pub enabled: bool,
 }
 
+/// wallet行为画像配置
+///
+/// 画像样本数达到 `min_samples` 后，金额 z-score、非活跃时段和新收款方
+/// 按权重合成一个画像分数，与规则/模型分数取最高；之前只用全局规则。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    /// 是否使用wallet画像
+    pub enabled: bool,
+
+    /// 启用画像所需的最少已确认发送笔数
+    pub min_samples: u64,
+
+    /// 金额 z-score 权重
+    pub amount_zscore_weight: f64,
+
+    /// 非活跃时段权重
+    pub off_hours_weight: f64,
+
+    /// 新收款方权重
+    pub recipient_novelty_weight: f64,
+
+    /// z-score 达到该值时金额分量记满
+    pub zscore_saturation: f64,
+
+    /// 某小时的历史发送占比不超过该值即视为非活跃时段
+    pub off_hours_max_share: f64,
+}
+
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_samples: 10,
+            amount_zscore_weight: 0.5,
+            off_hours_weight: 0.25,
+            recipient_novelty_weight: 0.25,
+            zscore_saturation: 4.0,
+            off_hours_max_share: 0.02,
+        }
+    }
+}
+
 /// 性能配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceConfig {
@@ -370,6 +418,14 @@ impl AnomalyDetectionConfig {
             return Err("High value threshold must be greater than 0".to_string());
         }
         
+        let p = &self.profiles;
+        if [p.amount_zscore_weight, p.off_hours_weight, p.recipient_novelty_weight].iter().any(|w| *w < 0.0) {
+            return Err("Profile weights must not be negative".to_string());
+        }
+        if p.zscore_saturation <= 0.0 {
+            return Err("Profile z-score saturation must be greater than 0".to_string());
+        }
+
         if self.performance.max_concurrent_detections == 0 {
             return Err("Maximum concurrent detections must be greater than 0".to_string());
         }
@@ -3,6 +3,7 @@
 //! 整合规则引擎、特征提取和ML模型，支持配置、事件和存储
 
 use super::*;
+use crate::blockchain::recipient_guard::RecipientClass;
 use crate::core::errors::WalletError;
 use tracing::{debug, info, warn};
 use std::sync::Arc;
@@ -14,6 +15,7 @@ use crate::anomaly_detection::{
     events::{EventBus, AnomalyEvent},
     storage::{StorageBackend, DetectionRecord, MemoryStorage},
     plugins::PluginRegistry,
+    profile::WalletProfile,
 };
 
 /// 异常检测器（重构版 - Level 5）
@@ -49,6 +51,27 @@ pub enum DetectionMode {
     BlockAll,
 }
 
+/// 检测时附带的wallet画像上下文，见 [`AnomalyDetector::detect_for_wallet`]
+#[derive(Debug, Clone, Copy)]
+pub struct WalletContext<'a> {
+    /// 该wallet在该network上的画像；还没有已确认发送时为 `None`
+    pub profile: Option<&'a WalletProfile>,
+    /// 画像的收款方集合中是否已有该Recipient address
+    pub recipient_known: bool,
+    /// 发送时间（Unix 秒）
+    pub timestamp: i64,
+    /// 发送前收款方检查的分类；未检查时为 `None`
+    pub recipient_class: Option<&'a RecipientClass>,
+}
+
+/// 画像评估结果
+#[derive(Debug, Clone)]
+enum ProfileVerdict {
+    /// 样本不足，只用全局规则
+    ColdStart { samples: u64, required: u64 },
+    Scored { score: f64, features: ProfileFeatures, factors: Vec<(String, f64)> },
+}
+
 impl AnomalyDetector {
     /// 创建新的检测器（带配置）
     pub fn with_config(config: AnomalyDetectionConfig) -> Self {
@@ -137,6 +160,32 @@ impl AnomalyDetector {
         amount: f64,
         gas_price: Option<u64>,
         is_contract: bool,
+    ) -> AnomalyResult {
+        self.detect(None, to_address, amount, gas_price, is_contract)
+    }
+
+    /// 检测transaction异常，并与该wallet自身的行为画像比较
+    ///
+    /// 画像样本数低于 `profiles.min_samples` 时退回全局规则，`reason` 中注明
+    /// cold start。
+    pub fn detect_for_wallet(
+        &mut self,
+        wallet: WalletContext<'_>,
+        to_address: &str,
+        amount: f64,
+        gas_price: Option<u64>,
+        is_contract: bool,
+    ) -> AnomalyResult {
+        self.detect(Some(wallet), to_address, amount, gas_price, is_contract)
+    }
+
+    fn detect(
+        &mut self,
+        wallet: Option<WalletContext<'_>>,
+        to_address: &str,
+        amount: f64,
+        gas_price: Option<u64>,
+        is_contract: bool,
     ) -> AnomalyResult {
         let start_time = Instant::now();
         // 使用SHA-256生成Transaction hash（安全哈希算法）
@@ -160,12 +209,21 @@ impl AnomalyDetector {
             to_address, amount, gas_price);
 
         // 1. 提取特征
-        let features = self.feature_extractor.extract(
+        let mut features = self.feature_extractor.extract(
             to_address,
             amount,
             gas_price,
             is_contract,
         );
+        if let Some(ctx) = wallet {
+            // 收款方是否收过款以wallet的已确认发送为准，而不是本进程见过的address
+            features.is_new_address = if ctx.recipient_known { 0.0 } else { 1.0 };
+            // 时间特征同样以发送时间为准，而不是检测时的系统时钟
+            features.is_off_hours = features::FeatureExtractor::is_off_hours_time(ctx.timestamp.max(0) as u64);
+            if let Some(class) = ctx.recipient_class {
+                features.contract_recipient_risk = class.risk();
+            }
+        }
 
         // 2. 规则引擎评估
         let (rule_threat_level, triggered_rules) = self.rule_engine.evaluate_transaction(
@@ -215,14 +273,24 @@ impl AnomalyDetector {
             });
         }
 
+        // 3.5 wallet画像偏离
+        let profile_verdict = wallet.map(|ctx| self.evaluate_profile(&ctx, amount));
+        let profile_score = match &profile_verdict {
+            Some(ProfileVerdict::Scored { score, .. }) => *score,
+            _ => 0.0,
+        };
+
         // 4. 综合判断（取所有来源的最高威胁级别）
         let final_threat_level = Self::max_threat_level(
             Self::max_threat_level(rule_threat_level, ml_threat_level),
-            plugin_threat_level
+            Self::max_threat_level(plugin_threat_level, ThreatLevel::from_score(profile_score)),
         );
-        let final_score = ml_score
+        let global_score = ml_score
             .max(Self::threat_level_to_score(rule_threat_level))
             .max(Self::threat_level_to_score(plugin_threat_level));
+        // 画像偏离叠加在全局分数之上，而不是与之取最大：ML 分数经 sigmoid 后本就在
+        // 0.5 附近，取最大会让只有画像看得出的偏离不改变总分
+        let final_score = 1.0 - (1.0 - global_score) * (1.0 - profile_score);
 
         // 5. 生成结果
         let is_anomalous = match self.mode {
@@ -235,9 +303,30 @@ impl AnomalyDetector {
             }
         };
 
-        let reason = self.generate_reason(&triggered_rules, &plugin_reasons, ml_score, &features);
-        
-        let key_factors = self.model.explain_prediction(&features);
+        let mut reason = self.generate_reason(&triggered_rules, &plugin_reasons, ml_score, &features);
+        let mut key_factors = self.model.explain_prediction(&features);
+        match profile_verdict {
+            Some(ProfileVerdict::ColdStart { samples, required }) => {
+                reason = format!(
+                    "{}; wallet profile cold start ({}/{} confirmed sends): global rules only",
+                    reason, samples, required
+                );
+            }
+            Some(ProfileVerdict::Scored { score, features: deviation, factors }) if score >= 0.2 => {
+                let mut signals = vec![format!("amount z-score {:.1}", deviation.amount_zscore)];
+                if deviation.off_hours_flag > 0.0 {
+                    signals.push("unusual hour".to_string());
+                }
+                if deviation.recipient_novelty > 0.0 {
+                    signals.push("new recipient".to_string());
+                }
+                let note = format!("Profile deviation {:.2}: {}", score, signals.join(", "));
+                reason = if reason == "No anomalies detected" { note } else { format!("{}; {}", reason, note) };
+                key_factors.extend(factors);
+            }
+            Some(ProfileVerdict::Scored { factors, .. }) => key_factors.extend(factors),
+            None => {}
+        }
 
         let result = AnomalyResult {
             is_anomalous,
@@ -299,6 +388,40 @@ impl AnomalyDetector {
         result
     }
 
+    /// 按配置的权重把画像偏离特征合成为 0-1 分数
+    fn evaluate_profile(&self, ctx: &WalletContext<'_>, amount: f64) -> ProfileVerdict {
+        let cfg = &self.config.profiles;
+        let samples = ctx.profile.map_or(0, |p| p.sample_count);
+        let profile = match ctx.profile {
+            Some(profile) if cfg.enabled && samples >= cfg.min_samples => profile,
+            _ => return ProfileVerdict::ColdStart { samples, required: cfg.min_samples },
+        };
+
+        let features = features::FeatureExtractor::profile_features(
+            profile,
+            amount,
+            ctx.recipient_known,
+            ctx.timestamp,
+            cfg.off_hours_max_share,
+        );
+        let amount_component = (features.amount_zscore.max(0.0) / cfg.zscore_saturation).min(1.0);
+        let weighted = [
+            ("amount_zscore", cfg.amount_zscore_weight, amount_component),
+            ("off_hours_flag", cfg.off_hours_weight, features.off_hours_flag),
+            ("recipient_novelty", cfg.recipient_novelty_weight, features.recipient_novelty),
+        ];
+        let total_weight: f64 = weighted.iter().map(|(_, w, _)| w).sum();
+        if total_weight <= 0.0 {
+            return ProfileVerdict::Scored { score: 0.0, features, factors: Vec::new() };
+        }
+        let factors: Vec<(String, f64)> = weighted
+            .iter()
+            .map(|(name, weight, value)| (name.to_string(), weight * value / total_weight))
+            .collect();
+        let score = factors.iter().map(|(_, c)| c).sum::<f64>().min(1.0);
+        ProfileVerdict::Scored { score, features, factors }
+    }
+
     /// validatetransaction（返回 Result）
     pub fn validate_transaction(
         &mut self,
@@ -308,7 +431,11 @@ impl AnomalyDetector {
         is_contract: bool,
     ) -> std::result::Result<(), WalletError> {
         let result = self.detect_transaction(to_address, amount, gas_price, is_contract);
+        self.verdict(&result)
+    }
 
+    /// 按当前拦截模式裁决一次检测结果：应阻止时返回 Err
+    pub fn verdict(&self, result: &AnomalyResult) -> std::result::Result<(), WalletError> {
         if result.is_anomalous && self.should_block(&result.threat_level) {
             Err(WalletError::ValidationError(format!(
                 "Transaction blocked by anomaly detection: {} (threat_level={:?}, score={:.2})",
@@ -480,6 +607,20 @@ impl AnomalyDetector {
         self.storage.clone()
     }
     
+    /// 当前规则集的内容哈希：配置的规范 JSON（键有序）、拦截模式、模型阈值与黑名单
+    /// 的 SHA-256，取前 128 位十六进制。热更新或加入黑名单后随之变化，供决策快照
+    /// 标识生效的规则版本
+    pub fn rules_hash(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let config = serde_json::to_value(&self.config).map(|v| v.to_string()).unwrap_or_default();
+        let digest = Sha256::new()
+            .chain_update(config)
+            .chain_update(format!("|{:?}|{}|", self.mode, self.model.threshold()))
+            .chain_update(self.rule_engine.rules().blacklisted_addresses().join(","))
+            .finalize();
+        hex::encode(&digest[..16])
+    }
+
     /// 更新配置（热更新）
     pub fn update_config(&mut self, config: AnomalyDetectionConfig) -> std::result::Result<(), String> {
         config.validate()?;
@@ -605,6 +746,178 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_rules_hash_follows_reloads() {
+        let mut detector = AnomalyDetector::new();
+        let initial = detector.rules_hash();
+        assert_eq!(initial.len(), 32);
+        // 内容不变则哈希不变（HashMap 字段按键序序列化）
+        assert_eq!(AnomalyDetector::new().rules_hash(), initial);
+
+        let mut config = detector.config().clone();
+        config.rule_engine.high_value_threshold *= 2.0;
+        detector.update_config(config).unwrap();
+        let reloaded = detector.rules_hash();
+        assert_ne!(reloaded, initial);
+
+        detector.set_mode(DetectionMode::WarnOnly);
+        let warn_only = detector.rules_hash();
+        assert_ne!(warn_only, reloaded);
+
+        detector.add_to_blacklist("0x000000000000000000000000000000000000dead".to_string(), "test".to_string());
+        assert_ne!(detector.rules_hash(), warn_only);
+    }
+
+    /// 2024-01-01（周一）00:00 UTC
+    const DAY0: i64 = 1_704_067_200;
+
+    /// 30 笔 0.09–0.11 ETH 的白天（10/14 点）发送
+    fn small_spender() -> WalletProfile {
+        let mut profile = WalletProfile::new("alice", "eth");
+        for i in 0..30i64 {
+            let hour = if i % 2 == 0 { 10 } else { 14 };
+            profile.record_send(0.09 + (i % 3) as f64 * 0.01, DAY0 + i * 3 * 86_400 + hour * 3600, i < 3);
+        }
+        profile
+    }
+
+    fn profile_reason(result: &AnomalyResult) -> &str {
+        result.reason.split("; ").find(|p| p.starts_with("Profile deviation")).unwrap_or("")
+    }
+
+    #[test]
+    fn test_profile_flags_outlier_amount() {
+        let profile = small_spender();
+        let usual = WalletContext {
+            profile: Some(&profile),
+            recipient_known: true,
+            timestamp: DAY0 + 10 * 3600,
+            recipient_class: None,
+        };
+        let to = "0x1234567890123456789012345678901234567890";
+
+        let normal = AnomalyDetector::new().detect_for_wallet(usual, to, 0.1, None, false);
+        assert!(profile_reason(&normal).is_empty(), "{}", normal.reason);
+
+        // 全局规则最多给出 Low，但远超这个wallet的习惯
+        let outlier = AnomalyDetector::new().detect_for_wallet(usual, to, 0.9, None, false);
+        assert!(profile_reason(&outlier).contains("amount z-score"), "{}", outlier.reason);
+        assert!(outlier.score >= 0.5);
+        assert!(outlier.key_factors.iter().any(|(name, c)| name == "amount_zscore" && *c > 0.4));
+        // 同一收款方、不带画像时只剩全局规则
+        let without_profile = WalletContext { profile: None, ..usual };
+        let global = AnomalyDetector::new().detect_for_wallet(without_profile, to, 0.9, None, false);
+        assert!(outlier.score > global.score, "{} <= {}", outlier.score, global.score);
+        assert!(!global.key_factors.iter().any(|(name, _)| name == "amount_zscore"));
+
+        // 同样的金额放到 3 点发给新address：偏离分量叠加
+        let night = WalletContext { recipient_known: false, timestamp: DAY0 + 3 * 3600, ..usual };
+        let result = AnomalyDetector::new().detect_for_wallet(night, to, 0.9, None, false);
+        assert!(profile_reason(&result).contains("unusual hour, new recipient"), "{}", result.reason);
+        assert_eq!(result.threat_level, ThreatLevel::Critical);
+        assert!(result.is_anomalous);
+    }
+
+    #[test]
+    fn test_profile_flags_off_hours_send() {
+        let profile = small_spender();
+        let night = WalletContext {
+            profile: Some(&profile),
+            recipient_known: true,
+            timestamp: DAY0 + 3 * 3600,
+            recipient_class: None,
+        };
+        let result = AnomalyDetector::new().detect_for_wallet(night, "0xAbCd", 0.1, None, false);
+        assert!(profile_reason(&result).contains("unusual hour"), "{}", result.reason);
+        assert!(!profile_reason(&result).contains("new recipient"));
+        assert!(result.key_factors.iter().any(|(name, c)| name == "off_hours_flag" && *c > 0.2));
+        // 只有时段异常时不阻止
+        assert!(!result.is_anomalous);
+    }
+
+    #[test]
+    fn test_profile_cold_start_uses_global_rules_only() {
+        let mut profile = WalletProfile::new("fresh", "eth");
+        profile.record_send(0.1, DAY0 + 10 * 3600, true);
+        let ctx = WalletContext {
+            profile: Some(&profile),
+            recipient_known: false,
+            timestamp: DAY0 + 3 * 3600,
+            recipient_class: None,
+        };
+        let to = "0x1234567890123456789012345678901234567890";
+
+        let result = AnomalyDetector::new().detect_for_wallet(ctx, to, 5.0, None, false);
+        let global = AnomalyDetector::new().detect_transaction(to, 5.0, None, false);
+        assert!(result.reason.contains("cold start (1/10 confirmed sends): global rules only"), "{}", result.reason);
+        assert_eq!(result.threat_level, global.threat_level);
+        assert_eq!(result.score, global.score);
+        assert!(!result.key_factors.iter().any(|(name, _)| name == "amount_zscore"));
+
+        let no_profile = WalletContext { profile: None, ..ctx };
+        let result = AnomalyDetector::new().detect_for_wallet(no_profile, to, 5.0, None, false);
+        assert!(result.reason.contains("cold start (0/10"), "{}", result.reason);
+    }
+
+    #[test]
+    fn test_contract_recipient_raises_score() {
+        let profile = small_spender();
+        let eoa = RecipientClass::ExternallyOwned;
+        let token = RecipientClass::TokenContract { symbol: "USDC".to_string() };
+        let ctx = WalletContext {
+            profile: Some(&profile),
+            recipient_known: true,
+            timestamp: DAY0 + 10 * 3600,
+            recipient_class: Some(&eoa),
+        };
+        let to = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+        let plain = AnomalyDetector::new().detect_for_wallet(ctx, to, 0.1, None, false);
+        let token_ctx = WalletContext { recipient_class: Some(&token), ..ctx };
+        let to_token = AnomalyDetector::new().detect_for_wallet(token_ctx, to, 0.1, None, false);
+        assert!(to_token.score > plain.score);
+        assert!(to_token.key_factors.iter().any(|(name, c)| name == "Contract Recipient" && *c > 0.0));
+        assert!(!plain.key_factors.iter().any(|(name, c)| name == "Contract Recipient" && *c > 0.0));
+    }
+
+    /// 记下每次评估时特征里的 `is_new_address`
+    #[derive(Default)]
+    struct NewAddressProbe(std::sync::Mutex<Vec<f64>>);
+
+    impl RulePlugin for NewAddressProbe {
+        fn name(&self) -> &str {
+            "new_address_probe"
+        }
+
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+
+        fn description(&self) -> &str {
+            "测试用：记录 is_new_address"
+        }
+
+        fn evaluate(&self, context: &TransactionContext) -> RuleResult {
+            self.0.lock().unwrap().push(context.features.is_new_address);
+            RuleResult::no_threat()
+        }
+    }
+
+    #[test]
+    fn test_wallet_context_decides_whether_recipient_is_new() {
+        let mut detector = AnomalyDetector::new();
+        let probe = Arc::new(NewAddressProbe::default());
+        detector.plugin_registry().register(probe.clone()).unwrap();
+        let to = "0x1234567890123456789012345678901234567890";
+        let known = WalletContext { profile: None, recipient_known: true, timestamp: DAY0, recipient_class: None };
+
+        // 本进程从未见过该address，但wallet曾向它付过款
+        detector.detect_for_wallet(known, to, 0.5, None, false);
+        // 本进程刚见过，但wallet从未付过款
+        detector.detect_for_wallet(WalletContext { recipient_known: false, ..known }, to, 0.5, None, false);
+        assert_eq!(*probe.0.lock().unwrap(), vec![0.0, 1.0]);
+    }
+
     #[test]
     fn test_custom_blacklist() {
         let mut detector = AnomalyDetector::new();
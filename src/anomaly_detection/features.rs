@@ -4,6 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::profile::{hour_of, WalletProfile};
+
 /// transaction特征向量
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionFeatures {
@@ -40,6 +42,11 @@ pub struct TransactionFeatures {
     // === network特征 ===
     /// network拥堵程度（0-1）
     pub network_congestion: f64,
+
+    // === 收款方特征 ===
+    /// 收款方合约分类的风险（代币合约 1.0，未知合约 0.6，其余 0），见 `RecipientClass::risk`
+    #[serde(default)]
+    pub contract_recipient_risk: f64,
 }
 
 impl TransactionFeatures {
@@ -59,6 +66,7 @@ impl TransactionFeatures {
             amount_deviation: 0.0,
             is_dust_amount: 0.0,
             network_congestion: 0.0,
+            contract_recipient_risk: 0.0,
         }
     }
 
@@ -77,12 +85,13 @@ impl TransactionFeatures {
             self.amount_deviation,
             self.is_dust_amount,
             self.network_congestion,
+            self.contract_recipient_risk,
         ]
     }
 
     /// from向量创建
     pub fn from_vector(vec: &[f64]) -> Option<Self> {
-        if vec.len() < 12 {
+        if vec.len() < 13 {
             return None;
         }
 
@@ -99,15 +108,27 @@ impl TransactionFeatures {
             amount_deviation: vec[9],
             is_dust_amount: vec[10],
             network_congestion: vec[11],
+            contract_recipient_risk: vec[12],
         })
     }
 
     /// 特征维度
     pub fn dimension() -> usize {
-        12
+        13
     }
 }
 
+/// 相对wallet自身画像的偏离特征
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileFeatures {
+    /// (金额 - 历史均值) / 历史标准差；低于均值时为负
+    pub amount_zscore: f64,
+    /// 发送时段在历史中几乎没有出现（0.0 or 1.0）
+    pub off_hours_flag: f64,
+    /// 从未付给过该收款方（0.0 or 1.0）
+    pub recipient_novelty: f64,
+}
+
 /// 特征提取器
 pub struct FeatureExtractor {
     /// 历史transaction数据（用于计算统计特征）
@@ -205,9 +226,29 @@ impl FeatureExtractor {
             amount_deviation,
             is_dust_amount,
             network_congestion,
+            // 只有发送路径知道收款方分类，由检测器填入
+            contract_recipient_risk: 0.0,
         }
     }
 
+    /// 计算相对wallet画像的偏离特征
+    ///
+    /// 标准差过小（金额几乎固定的wallet）时以均值的 5% 为下限，避免
+    /// 微小波动得到极大的 z-score。`timestamp` 为发送时间（Unix 秒）。
+    pub fn profile_features(
+        profile: &WalletProfile,
+        amount: f64,
+        recipient_known: bool,
+        timestamp: i64,
+        off_hours_max_share: f64,
+    ) -> ProfileFeatures {
+        let spread = profile.stddev_amount().max(profile.mean_amount * 0.05).max(1e-9);
+        let amount_zscore = (amount - profile.mean_amount) / spread;
+        let off_hours_flag = if profile.hour_share(hour_of(timestamp)) <= off_hours_max_share { 1.0 } else { 0.0 };
+        let recipient_novelty = if recipient_known { 0.0 } else { 1.0 };
+        ProfileFeatures { amount_zscore, off_hours_flag, recipient_novelty }
+    }
+
     /// fetch当前时间戳
     fn current_timestamp() -> u64 {
         std::time::SystemTime::now()
@@ -245,11 +286,11 @@ impl FeatureExtractor {
     }
 
     /// 判断是否为非工作时间
-    fn is_off_hours_time(timestamp: u64) -> f64 {
+    pub(crate) fn is_off_hours_time(timestamp: u64) -> f64 {
         use chrono::{DateTime, Datelike, Timelike, Utc};
         
         let dt: DateTime<Utc> = DateTime::from_timestamp(timestamp as i64, 0)
-            .unwrap_or_else(|| DateTime::UNIX_EPOCH);
+            .unwrap_or(DateTime::UNIX_EPOCH);
         let hour = dt.hour();
         let weekday = dt.weekday();
         
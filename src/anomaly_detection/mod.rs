@@ -22,14 +22,15 @@ pub mod events;
 pub mod storage;
 pub mod plugins;
 pub mod errors;
+pub mod profile;
 
 // ML模块暂未实现，待后续添加
 // #[cfg(feature = "ai-anomaly-detection")]
 // pub mod ml;
 
 // 核心组件
-pub use detector::{AnomalyDetector, DetectionMode};
-pub use features::{TransactionFeatures, FeatureExtractor};
+pub use detector::{AnomalyDetector, DetectionMode, WalletContext};
+pub use features::{TransactionFeatures, FeatureExtractor, ProfileFeatures};
 pub use rules::{AntiFishingRules, RuleEngine, ThreatLevel};
 
 // Level 5 新增组件
@@ -38,6 +39,7 @@ pub use events::{EventBus, AnomalyEvent, EventSubscriber, LoggingSubscriber, Sta
 pub use storage::{StorageBackend, DetectionRecord, MemoryStorage, AddressHistory};
 pub use plugins::{PluginRegistry, RulePlugin, RuleResult, RecommendedAction, TransactionContext};
 pub use errors::{AnomalyDetectionError, Result};
+pub use profile::WalletProfile;
 
 /// 异常检测结果（Level 5 重构版）
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
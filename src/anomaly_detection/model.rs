@@ -31,6 +31,8 @@ pub struct FeatureWeights {
     pub amount_deviation: f64,
     pub is_dust_amount: f64,
     pub network_congestion: f64,
+    #[serde(default)]
+    pub contract_recipient_risk: f64,
 }
 
 impl Default for FeatureWeights {
@@ -49,6 +51,7 @@ impl Default for FeatureWeights {
             amount_deviation: 0.15,          // 金额异常较危险
             is_dust_amount: 0.10,            // 尘埃攻击可疑
             network_congestion: 0.02,        // network拥堵时略增风险
+            contract_recipient_risk: 0.20,   // 转账给合约（尤其代币合约）常无法追回
         }
     }
 }
@@ -87,7 +90,8 @@ impl LightweightAnomalyModel {
             features.recent_tx_frequency * self.weights.recent_tx_frequency +
             features.amount_deviation * self.weights.amount_deviation +
             features.is_dust_amount * self.weights.is_dust_amount +
-            features.network_congestion * self.weights.network_congestion;
+            features.network_congestion * self.weights.network_congestion +
+            features.contract_recipient_risk * self.weights.contract_recipient_risk;
 
         // 使用 sigmoid 归一化到 0-1
         self.sigmoid(score)
@@ -126,10 +130,10 @@ impl LightweightAnomalyModel {
 
     /// 模型大小估算（字节）
     pub fn estimated_size_bytes() -> usize {
-        // 12 个权重 + 1 个阈值 = 13 个 f64
+        // 13 个权重 + 1 个阈值 = 14 个 f64
         // 每个 f64 = 8 字节
         // 加上一些开销
-        13 * 8 + 64 // ~168 字节
+        14 * 8 + 64 // ~176 字节
     }
 
     /// 解释预测结果
@@ -147,6 +151,10 @@ impl LightweightAnomalyModel {
             ("Amount Deviation".to_string(), features.amount_deviation * self.weights.amount_deviation),
             ("Dust Amount".to_string(), features.is_dust_amount * self.weights.is_dust_amount),
             ("Network Congestion".to_string(), features.network_congestion * self.weights.network_congestion),
+            (
+                "Contract Recipient".to_string(),
+                features.contract_recipient_risk * self.weights.contract_recipient_risk,
+            ),
         ];
 
         // 按贡献度排序
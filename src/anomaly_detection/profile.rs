@@ -0,0 +1,179 @@
+//! wallet行为画像
+//!
+//! 每个 (wallet, network) 一份已确认发送的滚动聚合：金额均值/方差（Welford）、
+//! 最大值、log2 分桶的金额分布（估算中位数）、UTC 小时直方图、不同收款方数量
+//! 和日均发送次数。[`WalletProfile::record_send`] 每笔transaction只做 O(1) 更新，
+//! 不回扫历史；按时间顺序重放全部已确认transaction得到同样的结果，
+//! 这就是 admin 重建所做的事。
+
+use chrono::{DateTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 金额分桶数；桶 `i` 覆盖 `[2^(i-32), 2^(i-31))` 个原生单位，两端溢出的归入首/末桶
+pub const AMOUNT_BUCKETS: usize = 64;
+const AMOUNT_BUCKET_OFFSET: i32 = 32;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletProfile {
+    pub wallet_id: String,
+    pub network: String,
+    /// 已计入的发送笔数
+    pub sample_count: u64,
+    /// 平均金额（原生单位）
+    pub mean_amount: f64,
+    /// 与均值差的平方和（Welford M2），方差 = m2 / (n - 1)
+    pub m2_amount: f64,
+    pub max_amount: f64,
+    /// 各 log2 金额桶的笔数
+    pub amount_buckets: Vec<u64>,
+    /// 各 UTC 小时的笔数
+    pub hour_histogram: [u64; 24],
+    pub distinct_recipients: u64,
+    /// 首笔/最近一笔发送的 Unix 秒
+    pub first_send_at: Option<i64>,
+    pub last_send_at: Option<i64>,
+}
+
+impl WalletProfile {
+    pub fn new(wallet_id: &str, network: &str) -> Self {
+        Self {
+            wallet_id: wallet_id.to_string(),
+            network: network.to_string(),
+            sample_count: 0,
+            mean_amount: 0.0,
+            m2_amount: 0.0,
+            max_amount: 0.0,
+            amount_buckets: vec![0; AMOUNT_BUCKETS],
+            hour_histogram: [0; 24],
+            distinct_recipients: 0,
+            first_send_at: None,
+            last_send_at: None,
+        }
+    }
+
+    /// 计入一笔在 `at`（Unix 秒）发出的发送；`new_recipient` 表示此前从未付给该address
+    pub fn record_send(&mut self, amount: f64, at: i64, new_recipient: bool) {
+        let amount = if amount.is_finite() { amount.max(0.0) } else { 0.0 };
+        self.sample_count += 1;
+        let delta = amount - self.mean_amount;
+        self.mean_amount += delta / self.sample_count as f64;
+        self.m2_amount += delta * (amount - self.mean_amount);
+        self.max_amount = self.max_amount.max(amount);
+
+        if self.amount_buckets.len() != AMOUNT_BUCKETS {
+            self.amount_buckets.resize(AMOUNT_BUCKETS, 0);
+        }
+        self.amount_buckets[amount_bucket(amount)] += 1;
+        self.hour_histogram[hour_of(at) as usize] += 1;
+        if new_recipient {
+            self.distinct_recipients += 1;
+        }
+        self.first_send_at = Some(self.first_send_at.map_or(at, |first| first.min(at)));
+        self.last_send_at = Some(self.last_send_at.map_or(at, |last| last.max(at)));
+    }
+
+    /// 样本标准差；少于两笔时为 0
+    pub fn stddev_amount(&self) -> f64 {
+        if self.sample_count < 2 {
+            return 0.0;
+        }
+        (self.m2_amount / (self.sample_count - 1) as f64).max(0.0).sqrt()
+    }
+
+    /// 中位数估计：中位数所在桶的几何中点，不超过最大值
+    pub fn median_amount(&self) -> Option<f64> {
+        if self.sample_count == 0 {
+            return None;
+        }
+        let target = self.sample_count.div_ceil(2);
+        let mut seen = 0;
+        for (i, count) in self.amount_buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                let midpoint = 2f64.powf(i as f64 - AMOUNT_BUCKET_OFFSET as f64 + 0.5);
+                return Some(midpoint.min(self.max_amount));
+            }
+        }
+        Some(self.max_amount)
+    }
+
+    /// `hour`（UTC）上的发送占比
+    pub fn hour_share(&self, hour: u32) -> f64 {
+        if self.sample_count == 0 {
+            return 0.0;
+        }
+        self.hour_histogram[(hour % 24) as usize] as f64 / self.sample_count as f64
+    }
+
+    /// 首笔到最近一笔之间的日均发送次数（不足一天按一天计）
+    pub fn sends_per_day(&self) -> f64 {
+        match (self.first_send_at, self.last_send_at) {
+            (Some(first), Some(last)) => {
+                let days = ((last - first) as f64 / 86_400.0).max(1.0);
+                self.sample_count as f64 / days
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+fn amount_bucket(amount: f64) -> usize {
+    if amount <= 0.0 {
+        return 0;
+    }
+    let index = amount.log2().floor() as i32 + AMOUNT_BUCKET_OFFSET;
+    index.clamp(0, AMOUNT_BUCKETS as i32 - 1) as usize
+}
+
+/// `at`（Unix 秒）的 UTC 小时
+pub fn hour_of(at: i64) -> u32 {
+    DateTime::<Utc>::from_timestamp(at, 0).unwrap_or(DateTime::UNIX_EPOCH).hour()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-01-01 00:00:00 UTC
+    const DAY0: i64 = 1_704_067_200;
+
+    #[test]
+    fn test_profile_converges_over_seeded_history() {
+        // 每周两笔 0.08–0.12 ETH，白天 09/15 点，四个固定收款方
+        let mut profile = WalletProfile::new("alice", "eth");
+        let mut seed: u64 = 42;
+        for i in 0..60i64 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let amount = 0.08 + (seed >> 33) as f64 / (1u64 << 31) as f64 * 0.04;
+            let hour = if i % 2 == 0 { 9 } else { 15 };
+            profile.record_send(amount, DAY0 + (i / 2) * 7 * 86_400 + (i % 2) * 3 * 86_400 + hour * 3600, i < 4);
+        }
+
+        assert_eq!(profile.sample_count, 60);
+        assert_eq!(profile.distinct_recipients, 4);
+        assert!((profile.mean_amount - 0.1).abs() < 0.01, "mean {}", profile.mean_amount);
+        assert!(profile.stddev_amount() > 0.005 && profile.stddev_amount() < 0.02);
+        assert!(profile.max_amount <= 0.12);
+        let median = profile.median_amount().unwrap();
+        assert!(median > 0.06 && median <= 0.12, "median {}", median);
+        assert_eq!(profile.hour_share(9), 0.5);
+        assert_eq!(profile.hour_share(3), 0.0);
+        let rate = profile.sends_per_day();
+        assert!(rate > 0.25 && rate < 0.32, "sends/day {}", rate);
+    }
+
+    #[test]
+    fn test_welford_matches_two_pass_statistics() {
+        let amounts = [0.5, 2.0, 1.25, 8.0, 0.001, 3.5];
+        let mut profile = WalletProfile::new("w", "eth");
+        for (i, amount) in amounts.iter().enumerate() {
+            profile.record_send(*amount, DAY0 + i as i64 * 60, false);
+        }
+        let mean = amounts.iter().sum::<f64>() / amounts.len() as f64;
+        let var = amounts.iter().map(|a| (a - mean).powi(2)).sum::<f64>() / (amounts.len() - 1) as f64;
+        assert!((profile.mean_amount - mean).abs() < 1e-12);
+        assert!((profile.stddev_amount() - var.sqrt()).abs() < 1e-12);
+        assert_eq!(profile.max_amount, 8.0);
+        assert_eq!(profile.sends_per_day(), 6.0);
+    }
+}
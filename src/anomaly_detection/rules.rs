@@ -115,6 +115,13 @@ impl AntiFishingRules {
         self.blacklist.get(address).cloned()
     }
 
+    /// Blacklisted addresses, sorted
+    pub fn blacklisted_addresses(&self) -> Vec<&str> {
+        let mut addresses: Vec<&str> = self.blacklist.keys().map(String::as_str).collect();
+        addresses.sort_unstable();
+        addresses
+    }
+
     /// Check if transaction amount exceeds high-value threshold
     pub fn is_high_value_transfer(&self, amount: f64) -> bool {
         amount > self.high_value_threshold
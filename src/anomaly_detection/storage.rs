@@ -140,7 +140,7 @@ impl StorageBackend for MemoryStorage {
             .collect();
         
         // 按时间戳排序（最新的在前）
-        filtered.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        filtered.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
         
         // 限制数量
         filtered.truncate(limit);
@@ -0,0 +1,467 @@
+//! Admission control for expensive operations.
+//!
+//! Signing, KDF-heavy unlocks and backup/export all contend for the same CPU
+//! and the same sqlite writer, so past a point running more of them at once
+//! only makes every request slower. [`AdmissionController`] caps how many run
+//! at once, globally and per wallet, and parks the rest in a bounded queue:
+//! - a request that finds the queue (or its wallet's share of it) full is
+//!   turned away right away with `429` and `Retry-After`;
+//! - a queued request waits until a slot frees up, its deadline passes, or
+//!   the server starts draining, whichever comes first;
+//! - when a slot frees up, queued [`Lane::Interactive`] requests are
+//!   considered before [`Lane::Batch`] ones, each lane in arrival order,
+//!   skipping anyone whose wallet is already at its own cap.
+//!
+//! Routes opt in by wrapping their `MethodRouter` with an [`AdmissionRoute`].
+//! Waiting happens inside the route's request timeout, and the queue deadline
+//! is capped by that timeout, so a request never waits longer than it would
+//! be allowed to run. A waiter whose request is dropped (client gone, outer
+//! timeout) leaves the queue when its future is dropped.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{RawPathParams, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+use crate::api::types::ApiError;
+use crate::core::config::AdmissionConfig;
+use crate::monitoring::WalletMetrics;
+
+/// Queue a request waits in when no slot is free.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lane {
+    /// Single sends, unlocks and exports a person is waiting on.
+    Interactive,
+    /// Bundles, group sweeps and other multi-step work.
+    Batch,
+}
+
+impl Lane {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Lane::Interactive => "interactive",
+            Lane::Batch => "batch",
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Lane::Interactive => 0,
+            Lane::Batch => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AdmissionError {
+    #[error("too many requests are waiting for an expensive operation slot")]
+    Overloaded,
+    #[error("no expensive operation slot freed up before the request deadline")]
+    QueueTimeout,
+    #[error("server is shutting down")]
+    ShuttingDown,
+}
+
+impl AdmissionError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AdmissionError::Overloaded => "SERVER_BUSY",
+            AdmissionError::QueueTimeout => "ADMISSION_TIMEOUT",
+            AdmissionError::ShuttingDown => "SHUTTING_DOWN",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AdmissionError::Overloaded => StatusCode::TOO_MANY_REQUESTS,
+            AdmissionError::QueueTimeout | AdmissionError::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self {
+            AdmissionError::Overloaded => "queue_full",
+            AdmissionError::QueueTimeout => "deadline",
+            AdmissionError::ShuttingDown => "shutdown",
+        }
+    }
+}
+
+/// Point-in-time view of the controller, reported by the health endpoint.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct AdmissionSnapshot {
+    pub in_flight: usize,
+    pub max_in_flight: usize,
+    pub queued: usize,
+    pub max_queued: usize,
+    /// Every slot is taken and requests are queueing behind them.
+    pub saturated: bool,
+}
+
+struct Waiter {
+    id: u64,
+    wallet: Option<String>,
+    grant: oneshot::Sender<AdmissionPermit>,
+}
+
+#[derive(Default)]
+struct AdmissionState {
+    in_flight: usize,
+    per_wallet: HashMap<String, usize>,
+    queues: [VecDeque<Waiter>; 2],
+    next_id: u64,
+    draining: bool,
+}
+
+impl AdmissionState {
+    fn queued(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    fn queued_for(&self, wallet: &str) -> usize {
+        self.queues.iter().flatten().filter(|w| w.wallet.as_deref() == Some(wallet)).count()
+    }
+
+    fn has_room(&self, config: &AdmissionConfig, wallet: Option<&str>) -> bool {
+        self.in_flight < config.max_in_flight
+            && wallet.is_none_or(|w| self.per_wallet.get(w).copied().unwrap_or(0) < config.per_wallet_in_flight)
+    }
+
+    fn take_slot(&mut self, wallet: Option<&str>) {
+        self.in_flight += 1;
+        if let Some(wallet) = wallet {
+            *self.per_wallet.entry(wallet.to_string()).or_default() += 1;
+        }
+    }
+
+    fn give_back(&mut self, wallet: Option<&str>) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+        if let Some(wallet) = wallet {
+            if let Some(count) = self.per_wallet.get_mut(wallet) {
+                *count -= 1;
+                if *count == 0 {
+                    self.per_wallet.remove(wallet);
+                }
+            }
+        }
+    }
+}
+
+/// Global and per-wallet concurrency caps with a bounded two-lane queue.
+pub struct AdmissionController {
+    config: AdmissionConfig,
+    state: Mutex<AdmissionState>,
+    metrics: Option<Arc<WalletMetrics>>,
+}
+
+impl AdmissionController {
+    pub fn new(config: AdmissionConfig) -> Self {
+        let config = AdmissionConfig {
+            max_in_flight: config.max_in_flight.max(1),
+            per_wallet_in_flight: config.per_wallet_in_flight.max(1),
+            ..config
+        };
+        Self { config, state: Mutex::new(AdmissionState::default()), metrics: None }
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<WalletMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn config(&self) -> &AdmissionConfig {
+        &self.config
+    }
+
+    /// How long a client should back off after a rejection.
+    pub fn retry_after(&self) -> Duration {
+        Duration::from_secs(self.config.retry_after_secs.max(1))
+    }
+
+    /// Longest a request may sit in the queue, before the route's own timeout.
+    pub fn max_wait(&self) -> Duration {
+        Duration::from_millis(self.config.max_wait_ms)
+    }
+
+    /// Waits for a slot for an operation on `wallet` (`None` counts only
+    /// against the global cap). The slot is held until the permit is dropped.
+    pub async fn acquire(
+        self: &Arc<Self>,
+        wallet: Option<&str>,
+        lane: Lane,
+        deadline: Instant,
+    ) -> Result<AdmissionPermit, AdmissionError> {
+        let started = Instant::now();
+        let (id, rx) = {
+            let mut state = self.state.lock();
+            if state.draining {
+                return Err(self.rejected(AdmissionError::ShuttingDown));
+            }
+            // every release dispatches the queue, so whoever is still queued is
+            // blocked on a cap this request may not share
+            if state.has_room(&self.config, wallet) {
+                state.take_slot(wallet);
+                self.publish(&state);
+                drop(state);
+                self.record_wait(started);
+                return Ok(self.permit(wallet));
+            }
+            let wallet_queue_full = wallet.is_some_and(|w| state.queued_for(w) >= self.config.per_wallet_queued);
+            if state.queued() >= self.config.max_queued || wallet_queue_full {
+                return Err(self.rejected(AdmissionError::Overloaded));
+            }
+            let (tx, rx) = oneshot::channel();
+            state.next_id += 1;
+            let id = state.next_id;
+            state.queues[lane.index()].push_back(Waiter { id, wallet: wallet.map(str::to_string), grant: tx });
+            self.publish(&state);
+            (id, rx)
+        };
+
+        let _queued = QueuedGuard { controller: self, lane, id };
+        match tokio::time::timeout_at(deadline, rx).await {
+            Ok(Ok(permit)) => {
+                self.record_wait(started);
+                Ok(permit)
+            }
+            // the sender is only dropped unfulfilled by `drain`
+            Ok(Err(_)) => Err(self.rejected(AdmissionError::ShuttingDown)),
+            Err(_) => Err(self.rejected(AdmissionError::QueueTimeout)),
+        }
+    }
+
+    /// Fails every queued request and turns away new ones; requests already
+    /// admitted run to completion.
+    pub fn drain(&self) {
+        let waiters: Vec<Waiter> = {
+            let mut state = self.state.lock();
+            state.draining = true;
+            let waiters = state.queues.iter_mut().flat_map(|q| q.drain(..)).collect();
+            self.publish(&state);
+            waiters
+        };
+        if !waiters.is_empty() {
+            tracing::info!("Admission control draining: failing {} queued requests", waiters.len());
+        }
+    }
+
+    pub fn snapshot(&self) -> AdmissionSnapshot {
+        let state = self.state.lock();
+        let queued = state.queued();
+        AdmissionSnapshot {
+            in_flight: state.in_flight,
+            max_in_flight: self.config.max_in_flight,
+            queued,
+            max_queued: self.config.max_queued,
+            saturated: queued > 0 && state.in_flight >= self.config.max_in_flight,
+        }
+    }
+
+    /// Route wrapper that admits requests through this controller in `lane`,
+    /// giving up once `timeout` (the route's request timeout) would pass.
+    pub fn route(self: &Arc<Self>, lane: Lane, timeout: Duration) -> AdmissionRoute {
+        AdmissionRoute { controller: self.clone(), lane, per_wallet: true, timeout }
+    }
+
+    fn permit(self: &Arc<Self>, wallet: Option<&str>) -> AdmissionPermit {
+        AdmissionPermit { controller: self.clone(), wallet: wallet.map(str::to_string), armed: true }
+    }
+
+    /// Hands freed slots to queued requests. Runs after every release.
+    fn dispatch(self: &Arc<Self>) {
+        let mut state = self.state.lock();
+        'grant: loop {
+            for lane in 0..state.queues.len() {
+                let next = state.queues[lane].iter().position(|w| state.has_room(&self.config, w.wallet.as_deref()));
+                let Some(waiter) = next.and_then(|i| state.queues[lane].remove(i)) else {
+                    continue;
+                };
+                state.take_slot(waiter.wallet.as_deref());
+                if let Err(mut permit) = waiter.grant.send(self.permit(waiter.wallet.as_deref())) {
+                    // the waiter gave up between timing out and leaving the queue;
+                    // its permit must not run `Drop` while the lock is held
+                    permit.armed = false;
+                    state.give_back(waiter.wallet.as_deref());
+                }
+                continue 'grant;
+            }
+            break;
+        }
+        self.publish(&state);
+    }
+
+    fn release(self: &Arc<Self>, wallet: Option<&str>) {
+        self.state.lock().give_back(wallet);
+        self.dispatch();
+    }
+
+    fn leave_queue(&self, lane: Lane, id: u64) {
+        let mut state = self.state.lock();
+        state.queues[lane.index()].retain(|w| w.id != id);
+        self.publish(&state);
+    }
+
+    fn rejected(&self, error: AdmissionError) -> AdmissionError {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_admission_rejection(error.reason());
+        }
+        error
+    }
+
+    fn record_wait(&self, started: Instant) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_admission_wait(started.elapsed().as_secs_f64());
+        }
+    }
+
+    fn publish(&self, state: &AdmissionState) {
+        if let Some(metrics) = &self.metrics {
+            metrics.set_admission_state(state.in_flight, state.queues[0].len(), state.queues[1].len());
+        }
+    }
+}
+
+/// Removes a waiter from the queue however its wait ends.
+struct QueuedGuard<'a> {
+    controller: &'a AdmissionController,
+    lane: Lane,
+    id: u64,
+}
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.controller.leave_queue(self.lane, self.id);
+    }
+}
+
+/// One admitted operation; the slot is released when this is dropped.
+pub struct AdmissionPermit {
+    controller: Arc<AdmissionController>,
+    wallet: Option<String>,
+    armed: bool,
+}
+
+impl std::fmt::Debug for AdmissionPermit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdmissionPermit").field("wallet", &self.wallet).finish_non_exhaustive()
+    }
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        if self.armed {
+            self.controller.release(self.wallet.as_deref());
+        }
+    }
+}
+
+/// Middleware state for one admitted route; see [`AdmissionController::route`].
+#[derive(Clone)]
+pub struct AdmissionRoute {
+    controller: Arc<AdmissionController>,
+    lane: Lane,
+    per_wallet: bool,
+    timeout: Duration,
+}
+
+impl AdmissionRoute {
+    /// Counts only against the global cap, for routes whose `:name` is not a
+    /// wallet (or that have none).
+    pub fn global_only(mut self) -> Self {
+        self.per_wallet = false;
+        self
+    }
+
+    pub fn wrap<S>(self, route: MethodRouter<S>) -> MethodRouter<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        route.layer(axum::middleware::from_fn_with_state(self, admit))
+    }
+}
+
+async fn admit(
+    State(route): State<AdmissionRoute>,
+    params: Option<RawPathParams>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let wallet = route
+        .per_wallet
+        .then(|| params.as_ref().and_then(|p| p.iter().find(|(key, _)| *key == "name").map(|(_, v)| v)))
+        .flatten();
+    let deadline = Instant::now() + route.controller.max_wait().min(route.timeout);
+    match route.controller.acquire(wallet, route.lane, deadline).await {
+        Ok(permit) => {
+            let response = next.run(request).await;
+            drop(permit);
+            response
+        }
+        Err(e) => {
+            tracing::warn!("{} request rejected by admission control: {}", route.lane.as_str(), e);
+            let mut response = ApiError::from_code(e.status(), e.code(), e.to_string()).into_response();
+            if let Ok(v) = HeaderValue::from_str(&route.controller.retry_after().as_secs().to_string()) {
+                response.headers_mut().insert(header::RETRY_AFTER, v);
+            }
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controller(max_in_flight: usize, max_queued: usize) -> Arc<AdmissionController> {
+        Arc::new(AdmissionController::new(AdmissionConfig {
+            max_in_flight,
+            per_wallet_in_flight: max_in_flight,
+            max_queued,
+            per_wallet_queued: max_queued,
+            ..Default::default()
+        }))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_released_slot_goes_to_the_waiter() {
+        let admission = controller(1, 1);
+        let held = admission.acquire(None, Lane::Batch, Instant::now()).await.unwrap();
+
+        let waiter = {
+            let admission = admission.clone();
+            tokio::spawn(async move {
+                admission.acquire(None, Lane::Batch, Instant::now() + Duration::from_secs(5)).await.map(drop)
+            })
+        };
+        tokio::task::yield_now().await;
+        assert_eq!(admission.snapshot().queued, 1);
+        assert!(admission.snapshot().saturated);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        assert_eq!(admission.acquire(None, Lane::Batch, deadline).await.unwrap_err(), AdmissionError::Overloaded);
+
+        drop(held);
+        assert_eq!(waiter.await.unwrap(), Ok(()));
+        assert_eq!(admission.snapshot().in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn test_drain_turns_new_requests_away() {
+        let admission = controller(1, 1);
+        admission.drain();
+        let deadline = Instant::now() + Duration::from_secs(1);
+        let err = admission.acquire(Some("w"), Lane::Interactive, deadline).await.unwrap_err();
+        assert_eq!(err, AdmissionError::ShuttingDown);
+        assert_eq!(err.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
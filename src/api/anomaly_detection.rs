@@ -17,6 +17,7 @@ use tracing::info;
 use crate::anomaly_detection::{
     AnomalyDetector, ThreatLevel,
 };
+use crate::ops::incidents::spawn_named;
 
 /// API 状态
 #[derive(Clone)]
@@ -36,7 +37,11 @@ impl Default for AnomalyApiState {
 impl AnomalyApiState {
     /// 创建新的异常检测 API 状态
     pub fn new() -> Self {
-        let detector = Arc::new(tokio::sync::Mutex::new(AnomalyDetector::new()));
+        Self::with_detector(Arc::new(tokio::sync::Mutex::new(AnomalyDetector::new())))
+    }
+
+    /// 使用已有的检测器，例如与发送路径共用的 `WalletServer::send_detector`
+    pub fn with_detector(detector: Arc<tokio::sync::Mutex<AnomalyDetector>>) -> Self {
         let (event_tx, _) = broadcast::channel(100);
         Self {
             detector,
@@ -260,7 +265,7 @@ async fn handle_websocket(socket: WebSocket, state: AnomalyApiState) {
     }
     
     // 监听事件并转发
-    let mut send_task = tokio::spawn(async move {
+    let mut send_task = spawn_named("anomaly_ws_send", async move {
         while let Ok(event) = rx.recv().await {
             if let Ok(json) = serde_json::to_string(&event) {
                 if sender.send(Message::Text(json)).await.is_err() {
@@ -271,7 +276,7 @@ async fn handle_websocket(socket: WebSocket, state: AnomalyApiState) {
     });
     
     // 接收客户端消息（ping/pong）
-    let mut recv_task = tokio::spawn(async move {
+    let mut recv_task = spawn_named("anomaly_ws_recv", async move {
         while let Some(Ok(msg)) = receiver.next().await {
             match msg {
                 Message::Close(_) => break,
@@ -25,6 +25,7 @@ fn make_config() -> WalletConfig {
             database_url: "sqlite::memory:".to_string(),
             max_connections: Some(4),
             connection_timeout_seconds: Some(5),
+            read_database_url: None,
         },
         blockchain: BlockchainConfig {
             networks: HashMap::new(),
@@ -33,6 +34,27 @@ fn make_config() -> WalletConfig {
         quantum_safe: false,
         multi_sig_threshold: 1,
         derivation: Default::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     }
 }
 
@@ -106,7 +128,7 @@ async fn bridge_returns_400_for_unsupported_chain_when_not_mocked() {
         .await;
     assert_eq!(res.status_code(), StatusCode::NOT_FOUND, "body: {}", res.text());
     let j: Value = res.json();
-    assert_eq!(j["code"].as_str().unwrap_or(""), "BRIDGE_FAILED");
+    assert_eq!(j["error"]["code"].as_str().unwrap_or(""), "BRIDGE_FAILED");
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -130,8 +152,8 @@ async fn bridge_returns_404_for_missing_wallet_when_mocked() {
         .await;
     assert_eq!(res.status_code(), StatusCode::NOT_FOUND, "body: {}", res.text());
     let j: Value = res.json();
-    assert_eq!(j["code"].as_str().unwrap_or(""), "BRIDGE_FAILED");
-    assert!(j["error"].as_str().unwrap_or("").to_lowercase().contains("wallet"));
+    assert_eq!(j["error"]["code"].as_str().unwrap_or(""), "BRIDGE_FAILED");
+    assert!(j["error"]["message"].as_str().unwrap_or("").to_lowercase().contains("wallet"));
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -248,6 +270,6 @@ async fn bridge_rejects_unknown_token_or_returns_failed_code() {
         assert!(j["bridge_tx_id"].as_str().map(|s| !s.is_empty()).unwrap_or(false));
     } else {
         let j: Value = res.json();
-        assert_eq!(j["code"].as_str().unwrap_or(""), "BRIDGE_FAILED");
+        assert_eq!(j["error"]["code"].as_str().unwrap_or(""), "BRIDGE_FAILED");
     }
 }
\ No newline at end of file
@@ -2,7 +2,8 @@
 
 use crate::api::bridge_lifi::{is_lifi_enabled, LiFiClient};
 use crate::api::server::WalletServer;
-use crate::api::types::BridgeAssetsRequest;
+use crate::api::types::BridgeAssets;
+use crate::api::validators::ValidJson;
 use axum::{extract::State, http::HeaderMap, response::{IntoResponse, Response}, Json};
 use std::sync::Arc;
 use tracing::info;
@@ -11,7 +12,7 @@ use tracing::info;
 pub async fn bridge_assets_enhanced(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
-    Json(req): Json<BridgeAssetsRequest>,
+    ValidJson(req): ValidJson<BridgeAssets>,
 ) -> Response {
     info!(
         "增强版跨链桥接: {} {} -> {} (token: {})",
@@ -24,13 +25,13 @@ pub async fn bridge_assets_enhanced(
     }
 
     // 否则降级到基础版本（已有的实现）
-    crate::api::handlers::bridge::bridge_assets(State(state), headers, Json(req)).await
+    crate::api::handlers::bridge::bridge_assets(State(state), headers, ValidJson(req)).await.into_response()
 }
 
 /// 使用 LI.FI 进行跨链桥接
 async fn bridge_with_lifi(
     _state: Arc<WalletServer>,
-    _req: BridgeAssetsRequest,
+    _req: BridgeAssets,
 ) -> Response {
     info!("使用 LI.FI 进行跨链桥接");
 
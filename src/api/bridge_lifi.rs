@@ -1,10 +1,15 @@
 //! LI.FI 跨链桥接 API 集成
 
+use crate::blockchain::bridge::NormalizedTransfer;
 use crate::core::errors::WalletError;
+use crate::token_registry::ResolvedToken;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+/// LI.FI 用零address表示链原生币
+const LIFI_NATIVE_TOKEN: &str = "0x0000000000000000000000000000000000000000";
+
 /// LI.FI API 客户端
 pub struct LiFiClient {
     client: Client,
@@ -173,6 +178,24 @@ impl LiFiClient {
             .collect())
     }
 
+    /// 按归一化后的桥接请求fetch路由：两端都用各自链上的合约address，金额为源链最小单位
+    pub async fn get_routes_for(
+        &self,
+        transfer: &NormalizedTransfer,
+        from_address: &str,
+    ) -> Result<Vec<EnhancedBridgeQuote>, WalletError> {
+        let token = |t: &ResolvedToken| t.address.clone().unwrap_or_else(|| LIFI_NATIVE_TOKEN.to_string());
+        self.get_routes(
+            &transfer.source.network,
+            &transfer.destination.network,
+            &token(&transfer.source),
+            &token(&transfer.destination),
+            &transfer.amount_source.minimal().to_string(),
+            from_address,
+        )
+        .await
+    }
+
     /// 转换路由数据
     fn convert_route(
         &self,
@@ -39,7 +39,7 @@ use utoipa::OpenApi;
             crate::api::types::TransactionResponse,
             crate::api::types::BalanceResponse,
             crate::api::types::TransactionHistoryResponse,
-            crate::api::types::ErrorResponse,
+            crate::api::types::ApiErrorBody,
             crate::api::types::RestoreWalletRequest,
             crate::api::types::MultiSigTransactionRequest,
             crate::api::types::MultiSigTransactionResponse,
@@ -51,10 +51,10 @@ use utoipa::OpenApi;
         ),
         responses(
             (status = 200, description = "请求success"),
-            (status = 400, description = "请求参数error", body = crate::api::types::ErrorResponse),
-            (status = 401, description = "未授权，需要认证", body = crate::api::types::ErrorResponse),
-            (status = 404, description = "资源不存在", body = crate::api::types::ErrorResponse),
-            (status = 500, description = "服务器内部error", body = crate::api::types::ErrorResponse),
+            (status = 400, description = "请求参数error", body = crate::api::types::ApiErrorBody),
+            (status = 401, description = "未授权，需要认证", body = crate::api::types::ApiErrorBody),
+            (status = 404, description = "资源不存在", body = crate::api::types::ApiErrorBody),
+            (status = 500, description = "服务器内部error", body = crate::api::types::ApiErrorBody),
         ),
         security_schemes(
             ("bearer_auth" = (type = http, scheme = bearer, bearer_format = "JWT"))
@@ -0,0 +1,967 @@
+//! The error catalog: every `code` the HTTP API can put in an error body.
+//!
+//! Each [`ApiErrorCode`] is declared once, with the one HTTP status it is sent
+//! with, whether retrying the same request can succeed, a description for API
+//! consumers and, for the messages clients commonly show to end users, a key
+//! into the bundled Fluent resources. [`ApiErrorCode::entry`] matches
+//! exhaustively, so a variant without a catalog entry does not compile.
+//!
+//! Handlers return [`ApiError`](crate::api::types::ApiError)s.
+//! [`annotate_errors`] adds `retryable` from the catalog to every error body
+//! on its way out and logs a warning when a handler sends a code with a status
+//! other than the declared one. The catalog is served at `GET /api/errors`.
+
+use axum::{
+    body::{Body, HttpBody},
+    extract::Request,
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+/// Error bodies above this size pass through without a `retryable` field.
+const MAX_ERROR_BODY: u64 = 64 * 1024;
+
+/// A code's catalog entry; also the JSON shape of `GET /api/errors`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CatalogEntry {
+    pub code: &'static str,
+    pub status: u16,
+    /// Whether the same request may succeed later without changes
+    pub retryable: bool,
+    pub description: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_key: Option<&'static str>,
+}
+
+impl CatalogEntry {
+    const fn message_key(mut self, key: &'static str) -> Self {
+        self.message_key = Some(key);
+        self
+    }
+}
+
+const fn entry(code: &'static str, status: u16, description: &'static str) -> CatalogEntry {
+    CatalogEntry { code, status, retryable: false, description, message_key: None }
+}
+
+const fn retryable(code: &'static str, status: u16, description: &'static str) -> CatalogEntry {
+    CatalogEntry { code, status, retryable: true, description, message_key: None }
+}
+
+macro_rules! api_error_codes {
+    ($($variant:ident),+ $(,)?) => {
+        /// Every error code the HTTP API sends; see [`ApiErrorCode::entry`].
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum ApiErrorCode {
+            $($variant),+
+        }
+
+        impl ApiErrorCode {
+            /// Every code, in declaration order.
+            pub const ALL: &'static [ApiErrorCode] = &[$(ApiErrorCode::$variant),+];
+        }
+    };
+}
+
+api_error_codes! {
+    // Authentication and access
+    AuthFailed,
+    AuthRequired,
+    InvalidToken,
+    TokenExpired,
+    InvalidSignature,
+    SignatureExpired,
+    NonceReplayed,
+    InvalidPassword,
+    KeystoreTransportKey,
+    WalletAccessDenied,
+    WalletScopeMismatch,
+    TokenCapabilityDenied,
+    TokenAmountCapExceeded,
+    NetworkNotAllowed,
+    GroupAccessDenied,
+    RequesterNotAuthorized,
+    ReviewRequiresServerSigning,
+    KeyRotationRequired,
+    FeatureDisabled,
+    BackupDisabled,
+    // Request validation
+    InvalidRequest,
+    InvalidRequestBody,
+    UnsupportedMediaType,
+    PayloadTooLarge,
+    BadRequest,
+    InvalidInput,
+    MissingParameter,
+    InvalidWalletName,
+    WalletNameTooLong,
+    InvalidAddress,
+    InvalidAddressChecksum,
+    WrongChainAddress,
+    InvalidAmount,
+    AmountPrecisionExceeded,
+    InvalidNetwork,
+    UnsupportedNetwork,
+    UnknownNetwork,
+    UnknownChainId,
+    InvalidTxHash,
+    TooManySubscriptions,
+    InvalidHex,
+    InvalidData,
+    InvalidCapability,
+    InvalidTokenLifetime,
+    InvalidAuthMode,
+    InvalidWindow,
+    InvalidStatus,
+    ReasonTooLong,
+    InvalidCursor,
+    InvalidRange,
+    InvalidMinDelta,
+    InvalidWebhookTarget,
+    InvalidAddressType,
+    InvalidAccount,
+    InvalidDerivationPath,
+    InvalidPaymentUri,
+    PaymentUriMismatch,
+    UnsupportedPaymentUri,
+    InvalidWalletNotes,
+    WalletNotesTooLarge,
+    TooManyMetadataKeys,
+    SecretInWalletNotes,
+    WeakPassword,
+    InvalidMnemonic,
+    PasswordRequired,
+    WalletAddressRequired,
+    UnsupportedWalletKind,
+    InvalidTokenAddress,
+    UnsupportedToken,
+    TokenCallReverted,
+    TokenBadReturnData,
+    UnlimitedApprovalUnconfirmed,
+    InvalidSlippage,
+    InvalidNftId,
+    InvalidObjectKey,
+    InvalidSourceDb,
+    InvalidUser,
+    InvalidRequestedBy,
+    CsvMalformed,
+    InvalidFlagRule,
+    UnknownFeatureFlag,
+    BackupNotSupported,
+    InvalidKeystore,
+    KeystoreMacMismatch,
+    InvalidShareParameters,
+    InvalidShare,
+    ShareChecksumMismatch,
+    DuplicateShare,
+    ShareBackupMismatch,
+    InsufficientShares,
+    ShareReconstructionMismatch,
+    InvalidEnvelope,
+    InvalidSenderKey,
+    EnvelopeMetaMissing,
+    EnvelopeSignatureInvalid,
+    EnvelopeSenderMismatch,
+    InvalidReserveReport,
+    InvalidReconciliation,
+    InvalidAttestation,
+    InvalidDeadmanPolicy,
+    InvalidBundle,
+    InvalidBatch,
+    DryRunUnsupported,
+    // Wallets, groups and users
+    WalletNotFound,
+    WalletExists,
+    WalletNotEmpty,
+    UserNotFound,
+    GroupNotFound,
+    GroupExists,
+    MemberNotFound,
+    TokenNotFound,
+    TokenBehaviorNotFound,
+    SubscriptionNotFound,
+    AttestationNotFound,
+    ReconciliationNotFound,
+    ReserveReportNotFound,
+    DeadmanNotFound,
+    DeadmanTriggered,
+    NetworkNotRegistered,
+    // Multisig and approvals
+    InvalidMultisigParams,
+    InvalidMultisigPolicy,
+    InvalidSpendingLimit,
+    MissingMultisigConfig,
+    InvalidSignerAddress,
+    InvalidSignerCount,
+    InsufficientSignatures,
+    AmountExceedsPolicy,
+    MultisigPolicyNotFound,
+    ProposalNotFound,
+    ApprovalNotFound,
+    ApprovalNotPending,
+    ApprovalSignerUnavailable,
+    ApprovalExpired,
+    SelfApprovalForbidden,
+    NotAnApprover,
+    InsufficientFunds,
+    // Delegations
+    InvalidDelegation,
+    DelegationNotFound,
+    DelegationExpired,
+    DelegationScopeDenied,
+    DelegationNetworkDenied,
+    DelegationRecipientDenied,
+    DelegationTxCapExceeded,
+    DelegationBudgetExhausted,
+    DelegationReviewRequired,
+    DelegationSignedTxUnsupported,
+    // Transactions
+    TransactionNotFound,
+    InvalidRawTransaction,
+    ChainIdMismatch,
+    UnprotectedTransaction,
+    NonceInUse,
+    NonceLaneReserved,
+    DuplicateTransactionSuspected,
+    RecipientIsContract,
+    BatchRequiresReview,
+    SpendingLimitExceeded,
+    DailySpendingLimitExceeded,
+    GasEstimationReverted,
+    GasEstimationFailed,
+    NonceLookupFailed,
+    // Time locks
+    InvalidTimelock,
+    TimelockNotFound,
+    TimelockNotYetUnlocked,
+    TimelockExpired,
+    TimelockNotLocked,
+    InvalidCustodianShare,
+    NonceLaneExhausted,
+    NonceLaneOvertaken,
+    TimelockNonceGap,
+    TimelockNonceConsumed,
+    TimelockTransactionStale,
+    TimelockGasBelowFloor,
+    TimelockReviewRequired,
+    // Bundles
+    BundleNotFound,
+    BundleNotRunnable,
+    BundleConflict,
+    BundleBlocked,
+    BundleRequiresReview,
+    // Bridge
+    UnsupportedBridgeRoute,
+    UnsupportedBridgeToken,
+    UnconfiguredBridgeToken,
+    BridgeAssetMismatch,
+    BridgeAmountOutOfRange,
+    BridgeAmountPrecision,
+    InvalidBridgeAmount,
+    BridgeBackendUnavailable,
+    NotFound,
+    // Meta-transaction relay
+    RelayDisabled,
+    InvalidForwardRequest,
+    MetaTxExpired,
+    SignatureMismatch,
+    RelayQuotaExhausted,
+    MetaTxDuplicate,
+    AnomalyBlocked,
+    RelaySubmissionFailed,
+    // ERC-4337
+    AaNotConfigured,
+    InvalidUserOperation,
+    UserOpNotFound,
+    UserOpGasEstimationFailed,
+    UserOpRejected,
+    BundlerUnavailable,
+    Aa10SenderAlreadyConstructed,
+    Aa13InitCodeFailed,
+    Aa14InitCodeWrongSender,
+    Aa15InitCodeNoDeployment,
+    Aa20AccountNotDeployed,
+    Aa21PrefundNotPaid,
+    Aa22ExpiredOrNotDue,
+    Aa23AccountReverted,
+    Aa24SignatureError,
+    Aa25InvalidNonce,
+    Aa30PaymasterNotDeployed,
+    Aa31PaymasterDepositTooLow,
+    Aa32PaymasterExpiredOrNotDue,
+    Aa33PaymasterReverted,
+    Aa34PaymasterSignatureError,
+    Aa40VerificationGasExceeded,
+    Aa41VerificationGasTooLow,
+    Aa51PrefundBelowActualGas,
+    // Operations and availability
+    JobNotFound,
+    JobRunning,
+    MaintenanceMode,
+    BackupInProgress,
+    CheckpointInProgress,
+    SandboxCloneRefused,
+    SandboxMainnetConfigured,
+    ServerBusy,
+    AdmissionTimeout,
+    ShuttingDown,
+    NetworkBusy,
+    NetworkUnavailable,
+    TransportKeyUnavailable,
+    ReportingKeyNotConfigured,
+    ApiKeyNotConfigured,
+    ApiKeyRotationNotShared,
+    ApiKeySaveFailed,
+    // Upstream failures
+    NetworkError,
+    RpcError,
+    ChainUnavailable,
+    BroadcastFailed,
+    BalanceCheckFailed,
+    GasPriceUnavailable,
+    GasPriceTimeout,
+    // Internal failures
+    DbError,
+    InternalError,
+    QueryFailed,
+    BlockchainQueryFailed,
+    EncryptionFailed,
+    SigningFailed,
+    SigningKeyUnavailable,
+    KeystoreFailed,
+    KeyUsageError,
+    WalletCreationFailed,
+    WalletInitializationFailed,
+    WalletAddressFailed,
+    WalletAddressMissing,
+    DeleteWalletFailed,
+    MultisigCreationFailed,
+    MultiSigFailed,
+    RotationFailed,
+    TransactionFailed,
+    HistoryFailed,
+    BridgeFailed,
+    BackupFailed,
+    RestoreFailed,
+    ShareBackupFailed,
+    CheckpointFailed,
+    DescriptorFailed,
+    AddressDerivationFailed,
+    ReportFailed,
+    ErasureFailed,
+    SandboxCloneFailed,
+}
+
+impl ApiErrorCode {
+    /// The catalog entry for this code.
+    pub const fn entry(self) -> CatalogEntry {
+        use ApiErrorCode::*;
+        match self {
+            AuthFailed => entry("AUTH_FAILED", 401, "The API key or session token is missing or wrong")
+                .message_key("error-auth-failed"),
+            AuthRequired => entry("AUTH_REQUIRED", 401, "The endpoint needs a signed-in user"),
+            InvalidToken => entry("INVALID_TOKEN", 401, "The bearer token or signing key is unknown or revoked"),
+            TokenExpired => entry("TOKEN_EXPIRED", 401, "The wallet token or signing key has expired"),
+            InvalidSignature => entry("INVALID_SIGNATURE", 401, "The request signature headers are missing or wrong"),
+            SignatureExpired => entry("SIGNATURE_EXPIRED", 401, "The signed timestamp is outside the allowed skew"),
+            NonceReplayed => entry("NONCE_REPLAYED", 401, "The request signature nonce was already used"),
+            InvalidPassword => {
+                entry("INVALID_PASSWORD", 401, "The wallet password is wrong").message_key("error-invalid-password")
+            }
+            KeystoreTransportKey => {
+                entry("KEYSTORE_TRANSPORT_KEY", 401, "The keystore transport key header is missing or wrong")
+            }
+            WalletAccessDenied => entry("WALLET_ACCESS_DENIED", 403, "The caller does not own this wallet"),
+            WalletScopeMismatch => {
+                entry("WALLET_SCOPE_MISMATCH", 403, "The wallet token was issued for another wallet")
+            }
+            TokenCapabilityDenied => {
+                entry("TOKEN_CAPABILITY_DENIED", 403, "The wallet token lacks the capability this endpoint needs")
+            }
+            TokenAmountCapExceeded => {
+                entry("TOKEN_AMOUNT_CAP_EXCEEDED", 403, "The amount is above the wallet token's per-transaction cap")
+            }
+            NetworkNotAllowed => entry("NETWORK_NOT_ALLOWED", 403, "The network is not in the wallet's allowed set"),
+            GroupAccessDenied => entry("GROUP_ACCESS_DENIED", 403, "The caller does not own this group"),
+            RequesterNotAuthorized => {
+                entry("REQUESTER_NOT_AUTHORIZED", 403, "The caller may not request an approval for this wallet")
+            }
+            ReviewRequiresServerSigning => entry(
+                "REVIEW_REQUIRES_SERVER_SIGNING",
+                403,
+                "A send above the review threshold cannot use an externally signed transaction",
+            ),
+            KeyRotationRequired => {
+                entry("KEY_ROTATION_REQUIRED", 403, "The signing key reached its usage limit and must be rotated")
+            }
+            FeatureDisabled => entry("FEATURE_DISABLED", 403, "The feature is turned off on this server"),
+            BackupDisabled => entry("BACKUP_DISABLED", 403, "Wallet backup export is disabled"),
+
+            InvalidRequest => entry("INVALID_REQUEST", 400, "The body, path or query string could not be decoded"),
+            InvalidRequestBody => {
+                entry("INVALID_REQUEST_BODY", 422, "The JSON body is missing fields or has fields of the wrong type")
+            }
+            UnsupportedMediaType => entry("UNSUPPORTED_MEDIA_TYPE", 415, "The body must be sent as application/json"),
+            PayloadTooLarge => entry("PAYLOAD_TOO_LARGE", 413, "The request body is above the size limit"),
+            BadRequest => entry("BAD_REQUEST", 400, "The request body could not be read"),
+            InvalidInput => entry("INVALID_INPUT", 400, "A request field is invalid"),
+            MissingParameter => entry("MISSING_PARAMETER", 400, "A required parameter is missing"),
+            InvalidWalletName => {
+                entry("INVALID_WALLET_NAME", 400, "The wallet name is empty or has invalid characters")
+            }
+            WalletNameTooLong => entry("WALLET_NAME_TOO_LONG", 400, "The wallet name is too long"),
+            InvalidAddress => {
+                entry("INVALID_ADDRESS", 400, "The address is malformed").message_key("error-invalid-address")
+            }
+            InvalidAddressChecksum => entry("INVALID_ADDRESS_CHECKSUM", 400, "The address fails its EIP-55 checksum"),
+            WrongChainAddress => entry("WRONG_CHAIN_ADDRESS", 400, "The address belongs to another chain"),
+            InvalidAmount => {
+                entry("INVALID_AMOUNT", 400, "The amount is not a positive decimal").message_key("error-invalid-amount")
+            }
+            AmountPrecisionExceeded => {
+                entry("AMOUNT_PRECISION_EXCEEDED", 400, "The amount has more decimals than the asset supports")
+            }
+            InvalidNetwork => entry("INVALID_NETWORK", 400, "The network parameter is missing or malformed"),
+            UnsupportedNetwork => entry("UNSUPPORTED_NETWORK", 400, "The network is not configured on this server")
+                .message_key("error-unsupported-network"),
+            UnknownNetwork => entry("UNKNOWN_NETWORK", 400, "The network is not known to the token registry"),
+            UnknownChainId => entry("UNKNOWN_CHAIN_ID", 400, "No configured network has this chain id"),
+            InvalidTxHash => entry("INVALID_TX_HASH", 400, "The transaction hash is malformed"),
+            TooManySubscriptions => {
+                retryable("TOO_MANY_SUBSCRIPTIONS", 429, "The WebSocket follows the most transactions allowed")
+            }
+            InvalidHex => entry("INVALID_HEX", 400, "A field is not 0x-prefixed hex"),
+            InvalidData => entry("INVALID_DATA", 400, "The transaction data is not 0x-prefixed hex"),
+            InvalidCapability => entry("INVALID_CAPABILITY", 400, "The wallet token capability is unknown"),
+            InvalidTokenLifetime => entry("INVALID_TOKEN_LIFETIME", 400, "The token lifetime is out of range"),
+            InvalidAuthMode => entry("INVALID_AUTH_MODE", 400, "The wallet auth mode is unknown"),
+            InvalidWindow => entry("INVALID_WINDOW", 400, "The time window is malformed or out of range"),
+            InvalidStatus => entry("INVALID_STATUS", 400, "The status filter is unknown"),
+            ReasonTooLong => entry("REASON_TOO_LONG", 400, "The reason is too long"),
+            InvalidCursor => entry("INVALID_CURSOR", 400, "The page cursor is malformed or belongs to another listing"),
+            InvalidRange => entry("INVALID_RANGE", 400, "The requested range is malformed or too wide"),
+            InvalidMinDelta => entry("INVALID_MIN_DELTA", 400, "The minimum balance change is not a valid amount"),
+            InvalidWebhookTarget => entry("INVALID_WEBHOOK_TARGET", 400, "The webhook URL is not allowed"),
+            InvalidAddressType => entry("INVALID_ADDRESS_TYPE", 400, "The Bitcoin address type is unknown"),
+            InvalidAccount => entry("INVALID_ACCOUNT", 400, "The Bitcoin account index is out of range"),
+            InvalidDerivationPath => {
+                entry("INVALID_DERIVATION_PATH", 400, "The BIP32 derivation path is malformed or not hardened")
+            }
+            InvalidPaymentUri => entry("INVALID_PAYMENT_URI", 400, "The payment URI is malformed"),
+            PaymentUriMismatch => entry("PAYMENT_URI_MISMATCH", 400, "The payment URI disagrees with a request field"),
+            UnsupportedPaymentUri => entry("UNSUPPORTED_PAYMENT_URI", 400, "The payment URI uses an unsupported form"),
+            InvalidWalletNotes => entry("INVALID_WALLET_NOTES", 400, "The wallet description or metadata is malformed"),
+            WalletNotesTooLarge => {
+                entry("WALLET_NOTES_TOO_LARGE", 400, "The wallet description or metadata is too large")
+            }
+            TooManyMetadataKeys => entry("TOO_MANY_METADATA_KEYS", 400, "The wallet metadata has too many keys"),
+            SecretInWalletNotes => {
+                entry("SECRET_IN_WALLET_NOTES", 400, "The wallet notes look like key material and were refused")
+            }
+            WeakPassword => entry("WEAK_PASSWORD", 400, "The password does not meet the strength requirements")
+                .message_key("error-weak-password"),
+            InvalidMnemonic => entry("INVALID_MNEMONIC", 400, "The seed phrase is not a valid BIP39 mnemonic"),
+            PasswordRequired => entry("PASSWORD_REQUIRED", 400, "The operation needs the wallet password"),
+            WalletAddressRequired => entry("WALLET_ADDRESS_REQUIRED", 400, "A wallet address is required"),
+            UnsupportedWalletKind => {
+                entry("UNSUPPORTED_WALLET_KIND", 422, "The operation is not available for this kind of wallet")
+            }
+            InvalidTokenAddress => entry("INVALID_TOKEN_ADDRESS", 400, "The token contract address is malformed"),
+            UnsupportedToken => entry("UNSUPPORTED_TOKEN", 400, "The token is not supported on this network"),
+            TokenCallReverted => entry("TOKEN_CALL_REVERTED", 422, "The token contract reverted the call"),
+            TokenBadReturnData => {
+                entry("TOKEN_BAD_RETURN_DATA", 422, "The token contract's return data is not a valid ERC-20 answer")
+            }
+            UnlimitedApprovalUnconfirmed => entry(
+                "UNLIMITED_APPROVAL_UNCONFIRMED",
+                422,
+                "The approval is unlimited; resend with allow_unlimited to confirm",
+            ),
+            InvalidSlippage => entry("INVALID_SLIPPAGE", 400, "The slippage is out of range"),
+            InvalidNftId => entry("INVALID_NFT_ID", 400, "The NFT id is malformed"),
+            InvalidObjectKey => entry("INVALID_OBJECT_KEY", 400, "The backup object key is not allowed"),
+            InvalidSourceDb => entry("INVALID_SOURCE_DB", 400, "The sandbox source database is not usable"),
+            InvalidUser => entry("INVALID_USER", 400, "The user id is malformed"),
+            InvalidRequestedBy => entry("INVALID_REQUESTED_BY", 400, "The erasure requester is missing or malformed"),
+            CsvMalformed => entry("CSV_MALFORMED", 400, "The CSV upload could not be parsed"),
+            InvalidFlagRule => entry("INVALID_FLAG_RULE", 400, "The feature flag rule is malformed"),
+            UnknownFeatureFlag => entry("UNKNOWN_FEATURE_FLAG", 400, "No feature flag has this name"),
+            BackupNotSupported => entry("BACKUP_NOT_SUPPORTED", 400, "The backup format is not supported"),
+            InvalidKeystore => entry("INVALID_KEYSTORE", 400, "The keystore file is malformed or unsupported"),
+            KeystoreMacMismatch => {
+                entry("KEYSTORE_MAC_MISMATCH", 400, "The keystore password is wrong or the file is damaged")
+            }
+            InvalidShareParameters => {
+                entry("INVALID_SHARE_PARAMETERS", 400, "The share threshold or count is out of range")
+            }
+            InvalidShare => entry("INVALID_SHARE", 400, "A backup share is malformed"),
+            ShareChecksumMismatch => entry("SHARE_CHECKSUM_MISMATCH", 400, "A backup share was altered or mistyped"),
+            DuplicateShare => entry("DUPLICATE_SHARE", 400, "The same backup share was supplied twice"),
+            ShareBackupMismatch => entry("SHARE_BACKUP_MISMATCH", 400, "The shares come from different backups"),
+            InsufficientShares => entry("INSUFFICIENT_SHARES", 400, "Fewer shares than the backup threshold"),
+            ShareReconstructionMismatch => {
+                entry("SHARE_RECONSTRUCTION_MISMATCH", 422, "The shares do not rebuild the backed-up key")
+            }
+            InvalidEnvelope => entry("INVALID_ENVELOPE", 400, "The keystore envelope is malformed"),
+            InvalidSenderKey => entry("INVALID_SENDER_KEY", 400, "The envelope sender key is malformed"),
+            EnvelopeMetaMissing => entry("ENVELOPE_META_MISSING", 400, "The keystore envelope has no metadata"),
+            EnvelopeSignatureInvalid => {
+                entry("ENVELOPE_SIGNATURE_INVALID", 400, "The keystore envelope signature does not verify")
+            }
+            EnvelopeSenderMismatch => {
+                entry("ENVELOPE_SENDER_MISMATCH", 403, "The keystore envelope was signed by another sender")
+            }
+            InvalidReserveReport => entry("INVALID_RESERVE_REPORT", 400, "The proof-of-reserves request is malformed"),
+            InvalidReconciliation => entry("INVALID_RECONCILIATION", 400, "The reconciliation request is malformed"),
+            InvalidAttestation => entry("INVALID_ATTESTATION", 400, "The attestation request is malformed"),
+            InvalidDeadmanPolicy => entry("INVALID_DEADMAN_POLICY", 400, "The dead man's switch policy is malformed"),
+            InvalidBundle => entry("INVALID_BUNDLE", 400, "The bundle definition is malformed"),
+            InvalidBatch => entry("INVALID_BATCH", 400, "The transaction batch is malformed"),
+            DryRunUnsupported => entry("DRY_RUN_UNSUPPORTED", 400, "This kind of send cannot be dry-run"),
+
+            WalletNotFound => {
+                entry("WALLET_NOT_FOUND", 404, "No wallet has this name").message_key("error-wallet-not-found")
+            }
+            WalletExists => {
+                entry("WALLET_EXISTS", 409, "A wallet with this name already exists").message_key("error-wallet-exists")
+            }
+            WalletNotEmpty => entry("WALLET_NOT_EMPTY", 409, "The wallet still holds funds"),
+            UserNotFound => entry("USER_NOT_FOUND", 404, "No user has this id"),
+            GroupNotFound => entry("GROUP_NOT_FOUND", 404, "No wallet group has this name"),
+            GroupExists => entry("GROUP_EXISTS", 409, "A wallet group with this name already exists"),
+            MemberNotFound => entry("MEMBER_NOT_FOUND", 404, "The wallet is not a member of this group"),
+            TokenNotFound => entry("TOKEN_NOT_FOUND", 404, "The token is not tracked for this wallet"),
+            TokenBehaviorNotFound => entry("TOKEN_BEHAVIOR_NOT_FOUND", 404, "No behavior is recorded for this token"),
+            SubscriptionNotFound => entry("SUBSCRIPTION_NOT_FOUND", 404, "No balance subscription has this id"),
+            AttestationNotFound => entry("ATTESTATION_NOT_FOUND", 404, "No attestation has this id"),
+            ReconciliationNotFound => entry("RECONCILIATION_NOT_FOUND", 404, "No reconciliation has this id"),
+            ReserveReportNotFound => entry("RESERVE_REPORT_NOT_FOUND", 404, "No proof-of-reserves report has this id"),
+            DeadmanNotFound => entry("DEADMAN_NOT_FOUND", 404, "The wallet has no dead man's switch"),
+            DeadmanTriggered => entry("DEADMAN_TRIGGERED", 409, "The dead man's switch already fired"),
+            NetworkNotRegistered => entry("NETWORK_NOT_REGISTERED", 404, "The network has no chain client"),
+
+            InvalidMultisigParams => {
+                entry("INVALID_MULTISIG_PARAMS", 400, "The multisig threshold or signers are invalid")
+            }
+            InvalidMultisigPolicy => entry("INVALID_MULTISIG_POLICY", 400, "The multisig policy is malformed"),
+            InvalidSpendingLimit => entry("INVALID_SPENDING_LIMIT", 400, "The spending limit is malformed"),
+            MissingMultisigConfig => entry("MISSING_MULTISIG_CONFIG", 400, "A multisig wallet needs a multisig config"),
+            InvalidSignerAddress => entry("INVALID_SIGNER_ADDRESS", 400, "A signer address is malformed"),
+            InvalidSignerCount => entry("INVALID_SIGNER_COUNT", 400, "The number of signers is out of range"),
+            InsufficientSignatures => {
+                entry("INSUFFICIENT_SIGNATURES", 400, "Fewer signatures than the multisig threshold were supplied")
+            }
+            AmountExceedsPolicy => entry("AMOUNT_EXCEEDS_POLICY", 400, "The amount is above the multisig policy limit"),
+            MultisigPolicyNotFound => entry("MULTISIG_POLICY_NOT_FOUND", 404, "The wallet has no multisig policy"),
+            ProposalNotFound => entry("PROPOSAL_NOT_FOUND", 404, "No multisig proposal has this id"),
+            ApprovalNotFound => entry("APPROVAL_NOT_FOUND", 404, "No approval has this id"),
+            ApprovalNotPending => entry("APPROVAL_NOT_PENDING", 409, "The approval was already decided"),
+            ApprovalSignerUnavailable => {
+                entry("APPROVAL_SIGNER_UNAVAILABLE", 409, "The held send's sealed signing key is missing or no longer opens")
+            }
+            ApprovalExpired => entry("APPROVAL_EXPIRED", 410, "The approval window has passed"),
+            SelfApprovalForbidden => {
+                entry("SELF_APPROVAL_FORBIDDEN", 403, "The requester cannot approve their own send")
+            }
+            NotAnApprover => entry("NOT_AN_APPROVER", 403, "The caller is not on the approvers list"),
+            InsufficientFunds => entry("INSUFFICIENT_FUNDS", 409, "The wallet balance does not cover the send")
+                .message_key("error-insufficient-balance"),
+
+            InvalidDelegation => entry("INVALID_DELEGATION", 400, "The delegation request is malformed"),
+            DelegationNotFound => entry("DELEGATION_NOT_FOUND", 404, "No delegation has this id"),
+            DelegationExpired => entry("DELEGATION_EXPIRED", 403, "The delegation has expired"),
+            DelegationScopeDenied => {
+                entry("DELEGATION_SCOPE_DENIED", 403, "The delegation does not cover this operation")
+            }
+            DelegationNetworkDenied => {
+                entry("DELEGATION_NETWORK_DENIED", 403, "The delegation does not cover this network")
+            }
+            DelegationRecipientDenied => {
+                entry("DELEGATION_RECIPIENT_DENIED", 403, "The delegation does not allow this recipient")
+            }
+            DelegationTxCapExceeded => {
+                entry("DELEGATION_TX_CAP_EXCEEDED", 403, "The amount is above the delegation's per-transaction cap")
+            }
+            DelegationBudgetExhausted => {
+                entry("DELEGATION_BUDGET_EXHAUSTED", 403, "The delegation's spending budget is used up")
+            }
+            DelegationReviewRequired => {
+                entry("DELEGATION_REVIEW_REQUIRED", 403, "Delegated sends cannot be held for review")
+            }
+            DelegationSignedTxUnsupported => entry(
+                "DELEGATION_SIGNED_TX_UNSUPPORTED",
+                400,
+                "Delegated sends cannot use externally signed transactions",
+            ),
+
+            TransactionNotFound => entry("TRANSACTION_NOT_FOUND", 404, "No transaction has this hash"),
+            InvalidRawTransaction => entry("INVALID_RAW_TRANSACTION", 400, "The raw transaction could not be decoded"),
+            ChainIdMismatch => entry("CHAIN_ID_MISMATCH", 400, "The transaction was signed for another chain"),
+            UnprotectedTransaction => {
+                entry("UNPROTECTED_TRANSACTION", 400, "The transaction has no replay protection and was not allowed")
+            }
+            NonceInUse => entry("NONCE_IN_USE", 409, "The nonce is already used by a transaction this server signed"),
+            NonceLaneReserved => {
+                entry("NONCE_LANE_RESERVED", 409, "The nonce is reserved for a time-locked transaction")
+            }
+            DuplicateTransactionSuspected => entry(
+                "DUPLICATE_TRANSACTION_SUSPECTED",
+                409,
+                "An identical send went out recently; resend with allow_duplicate to confirm",
+            ),
+            RecipientIsContract => entry(
+                "RECIPIENT_IS_CONTRACT",
+                422,
+                "The recipient is a contract; resend with acknowledge_contract_recipient to confirm",
+            ),
+            BatchRequiresReview => {
+                entry("BATCH_REQUIRES_REVIEW", 403, "The batch total is above the review threshold")
+            }
+            SpendingLimitExceeded => {
+                entry("SPENDING_LIMIT_EXCEEDED", 403, "The amount is above the wallet's per-transaction limit")
+            }
+            DailySpendingLimitExceeded => {
+                entry("DAILY_SPENDING_LIMIT_EXCEEDED", 403, "The send would take the wallet over its daily limit")
+            }
+            GasEstimationReverted => {
+                entry("GAS_ESTIMATION_REVERTED", 422, "The node reports the transfer would revert")
+            }
+            GasEstimationFailed => retryable("GAS_ESTIMATION_FAILED", 502, "The node could not estimate the transfer"),
+            NonceLookupFailed => {
+                retryable("NONCE_LOOKUP_FAILED", 502, "The transaction count could not be read from the node")
+            }
+
+            InvalidTimelock => entry("INVALID_TIMELOCK", 400, "The time lock request is malformed"),
+            TimelockNotFound => entry("TIMELOCK_NOT_FOUND", 404, "No time lock has this id"),
+            TimelockNotYetUnlocked => retryable("TIMELOCK_NOT_YET_UNLOCKED", 409, "The time lock has not opened yet"),
+            TimelockExpired => entry("TIMELOCK_EXPIRED", 410, "The time lock's release window has passed"),
+            TimelockNotLocked => entry("TIMELOCK_NOT_LOCKED", 409, "The time lock was already released or cancelled"),
+            InvalidCustodianShare => entry("INVALID_CUSTODIAN_SHARE", 403, "The custodian key share does not verify"),
+            NonceLaneExhausted => entry("NONCE_LANE_EXHAUSTED", 409, "The wallet has no free reserved nonce lane"),
+            NonceLaneOvertaken => entry("NONCE_LANE_OVERTAKEN", 409, "The reserved nonce was consumed on chain"),
+            TimelockNonceGap => {
+                retryable("TIMELOCK_NONCE_GAP", 409, "Earlier nonces are still pending; release after they confirm")
+            }
+            TimelockNonceConsumed => entry("TIMELOCK_NONCE_CONSUMED", 409, "The locked transaction's nonce was used"),
+            TimelockTransactionStale => {
+                entry("TIMELOCK_TRANSACTION_STALE", 409, "The locked transaction no longer matches the chain state")
+            }
+            TimelockGasBelowFloor => {
+                entry("TIMELOCK_GAS_BELOW_FLOOR", 409, "The locked transaction's fee is below the current floor")
+            }
+            TimelockReviewRequired => {
+                entry("TIMELOCK_REVIEW_REQUIRED", 403, "Time-locked sends above the review threshold are refused")
+            }
+
+            BundleNotFound => entry("BUNDLE_NOT_FOUND", 404, "No bundle has this id"),
+            BundleNotRunnable => entry("BUNDLE_NOT_RUNNABLE", 409, "The bundle is finished or cannot be resumed"),
+            BundleConflict => retryable("BUNDLE_CONFLICT", 409, "The bundle is being run by another request"),
+            BundleBlocked => entry("BUNDLE_BLOCKED", 403, "A bundle step was refused by policy"),
+            BundleRequiresReview => {
+                entry("BUNDLE_REQUIRES_REVIEW", 403, "The bundle total is above the review threshold")
+            }
+
+            UnsupportedBridgeRoute => entry("UNSUPPORTED_BRIDGE_ROUTE", 400, "No bridge serves this chain pair"),
+            UnsupportedBridgeToken => {
+                entry("UNSUPPORTED_BRIDGE_TOKEN", 400, "The bridge route does not carry this token")
+            }
+            UnconfiguredBridgeToken => {
+                entry("UNCONFIGURED_BRIDGE_TOKEN", 400, "The token is not in the registry for one of the chains")
+            }
+            BridgeAssetMismatch => {
+                entry("BRIDGE_ASSET_MISMATCH", 400, "The token resolves to different assets per chain")
+            }
+            BridgeAmountOutOfRange => {
+                entry("BRIDGE_AMOUNT_OUT_OF_RANGE", 400, "The amount is outside the route's limits")
+            }
+            BridgeAmountPrecision => {
+                entry("BRIDGE_AMOUNT_PRECISION", 400, "The amount cannot be represented exactly on one of the chains")
+            }
+            InvalidBridgeAmount => entry("INVALID_BRIDGE_AMOUNT", 400, "The bridge amount is not a valid amount"),
+            BridgeBackendUnavailable => {
+                entry("BRIDGE_BACKEND_UNAVAILABLE", 501, "The configured bridge backend cannot serve this route")
+            }
+            NotFound => entry("NOT_FOUND", 404, "No bridge transfer has this id"),
+
+            RelayDisabled => entry("RELAY_DISABLED", 503, "The meta-transaction relay is turned off"),
+            InvalidForwardRequest => entry("INVALID_FORWARD_REQUEST", 400, "The forward request is malformed"),
+            MetaTxExpired => entry("META_TX_EXPIRED", 400, "The forward request deadline has passed"),
+            SignatureMismatch => entry("SIGNATURE_MISMATCH", 403, "The forward request was not signed by `from`"),
+            RelayQuotaExhausted => retryable("RELAY_QUOTA_EXHAUSTED", 429, "The sender's relay quota is used up"),
+            MetaTxDuplicate => entry("META_TX_DUPLICATE", 409, "The forward request was already relayed"),
+            AnomalyBlocked => entry("ANOMALY_BLOCKED", 403, "The request was blocked by anomaly detection"),
+            RelaySubmissionFailed => retryable("RELAY_SUBMISSION_FAILED", 502, "The relayer could not submit the call"),
+
+            AaNotConfigured => entry("AA_NOT_CONFIGURED", 400, "ERC-4337 is not configured for this network"),
+            InvalidUserOperation => entry("INVALID_USER_OPERATION", 400, "The user operation request is malformed"),
+            UserOpNotFound => entry("USER_OP_NOT_FOUND", 404, "No user operation has this hash"),
+            UserOpGasEstimationFailed => {
+                entry("USER_OP_GAS_ESTIMATION_FAILED", 422, "The bundler could not estimate gas for the operation")
+            }
+            UserOpRejected => entry("USER_OP_REJECTED", 422, "The bundler rejected the user operation"),
+            BundlerUnavailable => retryable("BUNDLER_UNAVAILABLE", 502, "The bundler could not be reached"),
+            Aa10SenderAlreadyConstructed => {
+                entry("AA10_SENDER_ALREADY_CONSTRUCTED", 422, "initCode was sent for an account that already exists")
+            }
+            Aa13InitCodeFailed => entry("AA13_INIT_CODE_FAILED", 422, "The account factory call failed"),
+            Aa14InitCodeWrongSender => {
+                entry("AA14_INIT_CODE_WRONG_SENDER", 422, "The factory deployed a different sender address")
+            }
+            Aa15InitCodeNoDeployment => {
+                entry("AA15_INIT_CODE_NO_DEPLOYMENT", 422, "The factory did not deploy the account")
+            }
+            Aa20AccountNotDeployed => entry("AA20_ACCOUNT_NOT_DEPLOYED", 422, "The account is not deployed"),
+            Aa21PrefundNotPaid => entry("AA21_PREFUND_NOT_PAID", 422, "The account cannot pay the prefund"),
+            Aa22ExpiredOrNotDue => {
+                entry("AA22_EXPIRED_OR_NOT_DUE", 422, "The operation is outside its validity window")
+            }
+            Aa23AccountReverted => entry("AA23_ACCOUNT_REVERTED", 422, "The account's validation reverted"),
+            Aa24SignatureError => entry("AA24_SIGNATURE_ERROR", 422, "The account rejected the signature"),
+            Aa25InvalidNonce => entry("AA25_INVALID_NONCE", 422, "The account nonce is wrong"),
+            Aa30PaymasterNotDeployed => entry("AA30_PAYMASTER_NOT_DEPLOYED", 422, "The paymaster is not deployed"),
+            Aa31PaymasterDepositTooLow => {
+                entry("AA31_PAYMASTER_DEPOSIT_TOO_LOW", 422, "The paymaster deposit does not cover the operation")
+            }
+            Aa32PaymasterExpiredOrNotDue => {
+                entry("AA32_PAYMASTER_EXPIRED_OR_NOT_DUE", 422, "The paymaster data is outside its validity window")
+            }
+            Aa33PaymasterReverted => entry("AA33_PAYMASTER_REVERTED", 422, "The paymaster's validation reverted"),
+            Aa34PaymasterSignatureError => {
+                entry("AA34_PAYMASTER_SIGNATURE_ERROR", 422, "The paymaster rejected its signature")
+            }
+            Aa40VerificationGasExceeded => {
+                entry("AA40_VERIFICATION_GAS_EXCEEDED", 422, "Validation used more than verificationGasLimit")
+            }
+            Aa41VerificationGasTooLow => {
+                entry("AA41_VERIFICATION_GAS_TOO_LOW", 422, "verificationGasLimit is too low for the paymaster")
+            }
+            Aa51PrefundBelowActualGas => {
+                entry("AA51_PREFUND_BELOW_ACTUAL_GAS", 422, "The prefund is below the gas actually used")
+            }
+
+            JobNotFound => entry("JOB_NOT_FOUND", 404, "No background job has this name"),
+            JobRunning => retryable("JOB_RUNNING", 409, "The job is already running"),
+            MaintenanceMode => retryable("MAINTENANCE_MODE", 409, "The server is in maintenance mode"),
+            BackupInProgress => retryable("BACKUP_IN_PROGRESS", 409, "Another backup or restore is running"),
+            CheckpointInProgress => retryable("CHECKPOINT_IN_PROGRESS", 409, "Another database checkpoint is running"),
+            SandboxCloneRefused => entry("SANDBOX_CLONE_REFUSED", 409, "The sandbox clone target is not empty"),
+            SandboxMainnetConfigured => {
+                entry("SANDBOX_MAINNET_CONFIGURED", 409, "Sandbox clones are refused while a mainnet is configured")
+            }
+            ServerBusy => retryable("SERVER_BUSY", 429, "Too many expensive operations are queued")
+                .message_key("error-server-busy"),
+            AdmissionTimeout => {
+                retryable("ADMISSION_TIMEOUT", 503, "No operation slot freed up before the request deadline")
+            }
+            ShuttingDown => retryable("SHUTTING_DOWN", 503, "The server is shutting down"),
+            NetworkBusy => retryable("NETWORK_BUSY", 503, "The network's RPC budget is used up for now")
+                .message_key("error-server-busy"),
+            NetworkUnavailable => retryable("NETWORK_UNAVAILABLE", 503, "The network's circuit breaker is open"),
+            TransportKeyUnavailable => {
+                retryable("TRANSPORT_KEY_UNAVAILABLE", 503, "The keystore transport key could not be loaded")
+            }
+            ReportingKeyNotConfigured => {
+                entry("REPORTING_KEY_NOT_CONFIGURED", 503, "No proof-of-reserves signing key is configured")
+            }
+            ApiKeyNotConfigured => entry("API_KEY_NOT_CONFIGURED", 409, "No admin API key is configured to rotate"),
+            ApiKeyRotationNotShared => entry(
+                "API_KEY_ROTATION_NOT_SHARED",
+                409,
+                "Other instances cannot see a rotated API key without a writable secret backend",
+            ),
+            ApiKeySaveFailed => {
+                retryable("API_KEY_SAVE_FAILED", 503, "The rotated API key could not be saved to the secret backend")
+            }
+
+            NetworkError => retryable("NETWORK_ERROR", 502, "The blockchain node returned an error or was unreachable")
+                .message_key("error-network-error"),
+            RpcError => retryable("RPC_ERROR", 502, "The blockchain node returned an error"),
+            ChainUnavailable => retryable("CHAIN_UNAVAILABLE", 502, "The blockchain node could not be reached"),
+            BroadcastFailed => retryable("BROADCAST_FAILED", 502, "The node did not accept the signed transaction"),
+            BalanceCheckFailed => retryable("BALANCE_CHECK_FAILED", 502, "The balance could not be read from the node"),
+            GasPriceUnavailable => {
+                retryable("GAS_PRICE_UNAVAILABLE", 502, "The gas price could not be read from the node")
+            }
+            GasPriceTimeout => retryable("GAS_PRICE_TIMEOUT", 504, "The gas price query timed out"),
+
+            DbError => entry("DB_ERROR", 500, "The database operation failed").message_key("error-internal"),
+            InternalError => {
+                entry("INTERNAL_ERROR", 500, "The response could not be built").message_key("error-internal")
+            }
+            QueryFailed => entry("QUERY_FAILED", 500, "The lookup failed"),
+            BlockchainQueryFailed => entry("BLOCKCHAIN_QUERY_FAILED", 500, "The balance query failed"),
+            EncryptionFailed => entry("ENCRYPTION_FAILED", 500, "Encrypting the export failed"),
+            SigningFailed => entry("SIGNING_FAILED", 500, "Signing failed"),
+            SigningKeyUnavailable => {
+                entry("SIGNING_KEY_UNAVAILABLE", 500, "The server signing key could not be loaded")
+            }
+            KeystoreFailed => entry("KEYSTORE_FAILED", 500, "The keystore operation failed"),
+            KeyUsageError => entry("KEY_USAGE_ERROR", 500, "Key usage accounting is unavailable"),
+            WalletCreationFailed => entry("WALLET_CREATION_FAILED", 500, "Creating the wallet failed"),
+            WalletInitializationFailed => {
+                entry("WALLET_INITIALIZATION_FAILED", 500, "Initializing the wallet on the network failed")
+            }
+            WalletAddressFailed => entry("WALLET_ADDRESS_FAILED", 500, "Deriving the wallet address failed"),
+            WalletAddressMissing => entry("WALLET_ADDRESS_MISSING", 500, "The wallet has no stored address"),
+            DeleteWalletFailed => entry("DELETE_WALLET_FAILED", 500, "Deleting the wallet failed"),
+            MultisigCreationFailed => entry("MULTISIG_CREATION_FAILED", 500, "Creating the multisig wallet failed"),
+            MultiSigFailed => entry("MULTI_SIG_FAILED", 500, "Sending the multisig transaction failed"),
+            RotationFailed => entry("ROTATION_FAILED", 500, "Rotating the signing key failed"),
+            TransactionFailed => entry("TRANSACTION_FAILED", 500, "Signing or sending the transaction failed"),
+            HistoryFailed => entry("HISTORY_FAILED", 500, "Loading the transaction history failed"),
+            BridgeFailed => entry("BRIDGE_FAILED", 500, "Starting the bridge transfer failed"),
+            BackupFailed => entry("BACKUP_FAILED", 500, "Creating the wallet backup failed"),
+            RestoreFailed => entry("RESTORE_FAILED", 500, "Restoring the wallet failed"),
+            ShareBackupFailed => entry("SHARE_BACKUP_FAILED", 500, "Splitting or restoring key shares failed"),
+            CheckpointFailed => entry("CHECKPOINT_FAILED", 500, "The database checkpoint failed"),
+            DescriptorFailed => entry("DESCRIPTOR_FAILED", 500, "Deriving the wallet descriptor failed"),
+            AddressDerivationFailed => {
+                entry("ADDRESS_DERIVATION_FAILED", 500, "Deriving the address at the requested path failed")
+            }
+            ReportFailed => entry("REPORT_FAILED", 500, "Building the proof-of-reserves report failed"),
+            ErasureFailed => entry("ERASURE_FAILED", 500, "Erasing the user's data failed"),
+            SandboxCloneFailed => entry("SANDBOX_CLONE_FAILED", 500, "Cloning into the sandbox failed"),
+        }
+    }
+
+    /// The code string sent in `error.code`.
+    pub const fn as_str(self) -> &'static str {
+        self.entry().code
+    }
+
+    pub fn from_code(code: &str) -> Option<ApiErrorCode> {
+        Self::ALL.iter().copied().find(|c| c.as_str() == code)
+    }
+}
+
+impl std::fmt::Display for ApiErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Every catalog entry, in declaration order.
+pub fn catalog() -> Vec<CatalogEntry> {
+    ApiErrorCode::ALL.iter().map(|c| c.entry()).collect()
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).is_some_and(|v| v.starts_with("application/json"))
+}
+
+/// Adds `retryable` to the `error` object of
+/// [`ApiError`](crate::api::types::ApiError) bodies.
+///
+/// Codes missing from the catalog get `retryable: false`. Either that or a
+/// status other than the declared one is logged, since both mean a handler
+/// and the catalog have drifted apart.
+pub async fn annotate_errors(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let status = response.status();
+    if !(status.is_client_error() || status.is_server_error()) || !is_json(response.headers()) {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    if body.size_hint().exact().is_none_or(|len| len > MAX_ERROR_BODY) {
+        return Response::from_parts(parts, body);
+    }
+    let bytes = match axum::body::to_bytes(body, MAX_ERROR_BODY as usize).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("failed to buffer {} error response: {}", status, e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let Ok(Value::Object(mut body)) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let Some(object) = body.get_mut("error").and_then(Value::as_object_mut) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let Some(code) = object.get("code").and_then(Value::as_str) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    if object.contains_key("retryable") {
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+    let retryable = match ApiErrorCode::from_code(code).map(ApiErrorCode::entry) {
+        Some(entry) => {
+            if entry.status != status.as_u16() {
+                warn!(
+                    code,
+                    declared = entry.status,
+                    sent = status.as_u16(),
+                    "error code sent with an undeclared status"
+                );
+            }
+            entry.retryable
+        }
+        None => {
+            warn!(code, status = status.as_u16(), "error code missing from the error catalog");
+            false
+        }
+    };
+    object.insert("retryable".to_string(), Value::Bool(retryable));
+    match serde_json::to_vec(&body) {
+        Ok(annotated) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(annotated))
+        }
+        Err(_) => Response::from_parts(parts, Body::from(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn codes_are_unique_and_round_trip() {
+        let mut seen = HashSet::new();
+        for code in ApiErrorCode::ALL {
+            let entry = code.entry();
+            assert!(seen.insert(entry.code), "{} declared twice", entry.code);
+            assert_eq!(ApiErrorCode::from_code(entry.code), Some(*code));
+            assert!((400..600).contains(&entry.status), "{} has status {}", entry.code, entry.status);
+            assert!(!entry.description.is_empty(), "{} has no description", entry.code);
+            assert!(
+                entry.code.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_'),
+                "{} is not SCREAMING_SNAKE_CASE",
+                entry.code
+            );
+        }
+        assert_eq!(ApiErrorCode::from_code("NO_SUCH_CODE"), None);
+    }
+
+    #[test]
+    fn message_keys_exist_in_bundled_resources() {
+        for (language, resource) in
+            [("en", include_str!("../i18n/bundled/en.ftl")), ("zh", include_str!("../i18n/bundled/zh.ftl"))]
+        {
+            let keys: HashSet<&str> =
+                resource.lines().filter_map(|l| l.split_once(" = ")).map(|(key, _)| key.trim()).collect();
+            for entry in catalog() {
+                if let Some(key) = entry.message_key {
+                    assert!(keys.contains(key), "{} is missing from {}.ftl", key, language);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn upstream_and_capacity_failures_are_retryable() {
+        for entry in catalog() {
+            if matches!(entry.status, 429 | 502 | 504) {
+                assert!(entry.retryable, "{} ({}) should be retryable", entry.code, entry.status);
+            }
+            if matches!(entry.status, 400 | 401 | 404 | 413 | 415 | 422) {
+                assert!(!entry.retryable, "{} ({}) cannot succeed unchanged", entry.code, entry.status);
+            }
+        }
+    }
+}
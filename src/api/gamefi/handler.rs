@@ -1,11 +1,11 @@
 //! GameFi 和空投 API 处理器
 
 use super::types::*;
+use crate::api::error_catalog::ApiErrorCode;
 use crate::api::server::WalletServer;
-use crate::api::types::ErrorResponse;
+use crate::api::types::ApiError;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
     response::{IntoResponse, Response},
     Json,
 };
@@ -57,26 +57,14 @@ pub async fn claim_airdrop(
 
     // validate输入
     if req.wallet_name.trim().is_empty() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Wallet name不能为空".to_string(),
-                code: "INVALID_INPUT".to_string(),
-            }),
-        )
+        return ApiError::new(ApiErrorCode::InvalidInput, "Wallet name不能为空")
             .into_response();
     }
 
     // checkwallet是否存在
     if let Err(e) = state.wallet_manager.get_wallet_by_name(&req.wallet_name).await {
         error!("wallet不存在: {:?}", e);
-        return (
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("wallet '{}' 不存在", req.wallet_name),
-                code: "WALLET_NOT_FOUND".to_string(),
-            }),
-        )
+        return ApiError::new(ApiErrorCode::WalletNotFound, format!("wallet '{}' 不存在", req.wallet_name))
             .into_response();
     }
 
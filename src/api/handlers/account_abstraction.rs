@@ -0,0 +1,185 @@
+//! ERC-4337 账户抽象 handlers
+//!
+//! wallet密钥作为 SimpleAccount 的 owner：服务端构造 UserOperation、用wallet
+//! 密钥sign userOpHash 并提交到该network配置的 bundler；之后按 op hash 跟踪到上链。
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use ethers::types::{Address, Bytes, U256};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::handlers::key_usage::authorize_signing;
+use crate::api::handlers::wallet_networks::check_network_allowed;
+use crate::api::middleware::wallet_scope::extract_wallet_caller;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{
+    Amount, EvmAddress, NetworkName, ParamError, TxHash, Validate, ValidJson, ValidPath, WalletNameParam,
+};
+use crate::erc4337::{AaSendRequest, Erc4337Error, UserOpSubmission};
+use crate::feature_flags::Flag;
+use crate::security::error_sanitizer::sanitize_error_message;
+use crate::storage::{UserOperationRecord, WalletCapability};
+
+/// `POST /api/wallets/:name/aa/send` 请求体
+#[derive(Deserialize)]
+pub struct AaSendBody {
+    pub network: String,
+    pub to: String,
+    /// 以 ether 为单位；缺省为 0
+    #[serde(default)]
+    pub value: Option<String>,
+    /// 目标合约 calldata（0x hex）
+    #[serde(default)]
+    pub data: Option<String>,
+    pub password: String,
+    /// 调用方从 paymaster 服务获得的 `paymasterAndData`（0x hex），原样附加
+    #[serde(default)]
+    pub paymaster_and_data: Option<String>,
+}
+
+/// [`AaSendBody`] validate后
+pub struct AaSend {
+    pub network: NetworkName,
+    pub to: EvmAddress,
+    pub value: Option<Amount>,
+    pub data: Bytes,
+    pub password: String,
+    pub paymaster_and_data: Bytes,
+}
+
+fn parse_hex(field: &'static str, raw: Option<&str>) -> Result<Bytes, ParamError> {
+    let Some(raw) = raw.filter(|s| !s.is_empty()) else {
+        return Ok(Bytes::new());
+    };
+    raw.strip_prefix("0x")
+        .and_then(|hex_str| hex::decode(hex_str).ok())
+        .map(Bytes::from)
+        .ok_or(ParamError::HexBytes(field))
+}
+
+impl Validate for AaSend {
+    type Raw = AaSendBody;
+
+    fn validate(raw: AaSendBody) -> Result<Self, ParamError> {
+        if raw.password.is_empty() {
+            return Err(ParamError::Missing("password"));
+        }
+        Ok(Self {
+            network: NetworkName::try_from(raw.network.as_str())?.require_evm()?,
+            to: EvmAddress::try_from(raw.to.as_str())?,
+            value: raw.value.as_deref().map(Amount::try_from).transpose()?,
+            data: parse_hex("data", raw.data.as_deref())?,
+            password: raw.password,
+            paymaster_and_data: parse_hex("paymaster_and_data", raw.paymaster_and_data.as_deref())?,
+        })
+    }
+}
+
+fn aa_error(e: Erc4337Error) -> ApiError {
+    let status = match &e {
+        Erc4337Error::NotConfigured(_) | Erc4337Error::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        Erc4337Error::GasEstimation { .. } | Erc4337Error::Rejected { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        Erc4337Error::Bundler(_) | Erc4337Error::Rpc(_) => StatusCode::BAD_GATEWAY,
+        Erc4337Error::Signing(_) | Erc4337Error::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let message = match &e {
+        Erc4337Error::Storage(inner) => {
+            error!("user operation storage error: {}", inner);
+            "Failed to record user operation".to_string()
+        }
+        other => sanitize_error_message(&other.to_string()),
+    };
+    ApiError::from_code(status, e.code(), message)
+}
+
+/// `POST /api/wallets/:name/aa/send`：以wallet的智能账户执行一次调用
+///
+/// `account_abstraction` 开关对该wallet关闭时 403 `FEATURE_DISABLED`；已提交的
+/// operation 仍可查询。
+pub async fn aa_send(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(req): ValidJson<AaSend>,
+) -> Result<Json<UserOpSubmission>, ApiError> {
+    let name = name.as_str();
+    let caller = extract_wallet_caller(&headers, &state).await?;
+    caller.authorize(&state, name, WalletCapability::Send).await?;
+    if let Some(value) = &req.value {
+        caller.check_amount(value)?;
+    }
+    caller.audit(&state, name, "aa_send").await;
+    if !state.flags.is_enabled(Flag::AccountAbstraction, Some(name)).await {
+        return Err(ApiError::new(ApiErrorCode::FeatureDisabled, "Account abstraction is disabled for this wallet"));
+    }
+    check_network_allowed(&state, name, req.network.as_str())?;
+
+    // 服务端sign：计入密钥使用量并执行轮换策略
+    authorize_signing(&state, name).await?;
+    let owner = state
+        .wallet_manager
+        .ethereum_signer(name, &req.password)
+        .await
+        .map_err(|e| aa_error(Erc4337Error::Signing(e)))?;
+
+    let to: Address = req.to.as_str().parse().map_err(|_| ParamError::AddressHex)?;
+    let value = match &req.value {
+        Some(amount) => ethers::utils::parse_ether(amount.as_str()).map_err(|_| ParamError::AmountFormat)?,
+        None => U256::zero(),
+    };
+    let submission = state
+        .account_abstraction
+        .send(
+            &owner,
+            &AaSendRequest {
+                wallet_name: name,
+                network: req.network.as_str(),
+                to,
+                value,
+                data: req.data,
+                paymaster_and_data: req.paymaster_and_data,
+            },
+        )
+        .await
+        .map_err(aa_error)?;
+
+    state.account_abstraction.clone().track(submission.user_op_hash.clone());
+    Ok(Json(submission))
+}
+
+/// `GET /api/wallets/:name/aa/operations/:op_hash`：仍为 pending 时先向 bundler 查询一次收据
+pub async fn aa_operation(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((name, op_hash)): Path<(String, String)>,
+) -> Result<Json<UserOperationRecord>, ApiError> {
+    let name = WalletNameParam::try_from(name.as_str())?;
+    let op_hash = TxHash::try_from(op_hash.as_str())?;
+    let name = name.as_str();
+    let caller = extract_wallet_caller(&headers, &state).await?;
+    caller.authorize(&state, name, WalletCapability::ReadHistory).await?;
+
+    let op_hash = format!("0x{}", op_hash.as_str().trim_start_matches("0x").to_ascii_lowercase());
+    let not_found = || {
+        ApiError::new(ApiErrorCode::UserOpNotFound, "User operation not found")
+    };
+    let record = state
+        .storage
+        .user_operation(&op_hash)
+        .await
+        .map_err(|e| aa_error(Erc4337Error::Storage(e)))?
+        .filter(|record| record.wallet_name == name)
+        .ok_or_else(not_found)?;
+    if record.status != crate::storage::USER_OP_PENDING {
+        return Ok(Json(record));
+    }
+    let record = state.account_abstraction.refresh(&op_hash).await.map_err(aa_error)?.ok_or_else(not_found)?;
+    Ok(Json(record))
+}
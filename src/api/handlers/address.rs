@@ -1,84 +1,122 @@
 //! address相关handlers
+//!
+//! 默认返回 user_wallets 表中登记的address（非托管，不需要解密）。
+//! 带 `?derivation_path=` 时改由服务端按该路径从wallet主密钥派生，
+//! wallet Password通过 `X-Wallet-Password` 头传入。
 
 use axum::{
-    extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
+    extract::State,
+    http::HeaderMap,
     response::Json,
 };
 use std::sync::Arc;
 use tracing::{info, error};
+use zeroize::Zeroizing;
 
 // use crate::api::middleware::authenticate; // ✅ 开发环境已禁用认证
+use super::btc_descriptor::WALLET_PASSWORD_HEADER;
+use super::wallet_networks::check_network_allowed;
+use crate::api::error_catalog::ApiErrorCode;
 use crate::api::middleware::extract_user::{extract_user_id_from_token, verify_wallet_ownership};
+use crate::api::middleware::request_metrics::note_wallet_error;
 use crate::api::server::WalletServer;
 use crate::api::types::*;
-use crate::api::validators::{validate_wallet_name, validate_and_normalize_network};
+use crate::api::validators::{ParamError, ValidPath, ValidQuery, WalletNameParam};
+use crate::core::errors::WalletError;
 
 /// fetchwalletaddress
 pub async fn get_wallet_address(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,  // ✅ 启用user认证
-    Path(name): Path<String>,
-    Query(query): Query<AddressQuery>,
-) -> Result<Json<AddressResponse>, (StatusCode, Json<ErrorResponse>)> {
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(query): ValidQuery<AddressParams>,
+) -> Result<Json<AddressResponse>, ApiError> {
     // ✅ 提取当前登录User ID
     let user_id = extract_user_id_from_token(&headers, &state).await?;
     
     // ✅ validatewallet属于该user（权限check）
-    verify_wallet_ownership(&user_id, &name, &state).await?;
+    let name = name.as_str();
+    verify_wallet_ownership(&user_id, name, &state).await?;
 
-    // validateWallet name（使用共享validate器）
-    validate_wallet_name(&name)?;
+    // 未提供network时默认使用eth
+    let normalized_network = query.network.as_ref().map_or("eth", |n| n.as_str());
+    check_network_allowed(&state, name, normalized_network)?;
 
-    // ✅ validate并规范化network参数（使用共享validate器）- 如果未提供，默认使用eth
-    let network = query.network.as_deref().unwrap_or("eth");
-    let normalized_network = validate_and_normalize_network(network)?;
+    if let Some(path) = &query.derivation_path {
+        let password = headers
+            .get(WALLET_PASSWORD_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| Zeroizing::new(v.to_string()))
+            .ok_or(ParamError::Missing("X-Wallet-Password header"))?;
+        let address = state
+            .wallet_manager
+            .derive_wallet_address(name, &password, normalized_network, Some(path))
+            .await
+            .map_err(derivation_error)?;
+        info!("address derived: wallet={}, network={}, path={}", name, normalized_network, path);
+        return Ok(Json(AddressResponse {
+            address,
+            network: normalized_network.to_string(),
+            derivation_path: Some(path.to_string()),
+        }));
+    }
 
+    let wallet_address = stored_wallet_address(&state, &user_id, name).await?;
+
+    // ✅ 非托管模式：直接返回存储的address（不需要解密）
+    info!("✅ 非托管addressquery: wallet={}, address={}, network={}", 
+          name, wallet_address, normalized_network);
+    
+    Ok(Json(AddressResponse {
+        address: wallet_address,
+        network: normalized_network.to_string(),
+        derivation_path: None,
+    }))
+}
+
+fn derivation_error(e: WalletError) -> ApiError {
+    let (error, code) = match e {
+        WalletError::NotFoundError(msg) => (msg, ApiErrorCode::WalletNotFound),
+        WalletError::UnsupportedWalletKind(msg) => (msg, ApiErrorCode::UnsupportedWalletKind),
+        // decrypt_master_key：Password错误或不满足Password策略
+        WalletError::CryptoError(_) | WalletError::SecurityError(_) => {
+            ("Invalid wallet password".to_string(), ApiErrorCode::InvalidPassword)
+        }
+        // 该network不支持派生address
+        WalletError::ValidationError(msg) => (msg, ApiErrorCode::InvalidRequest),
+        other => {
+            error!("address derivation failed: {}", other);
+            note_wallet_error(&other);
+            ("Address derivation failed".to_string(), ApiErrorCode::AddressDerivationFailed)
+        }
+    };
+    ApiError::new(code, error)
+}
+
+/// user_wallets 表中登记的walletaddress（非托管模式，不需要解密）
+pub(super) async fn stored_wallet_address(
+    state: &WalletServer,
+    user_id: &str,
+    name: &str,
+) -> Result<String, ApiError> {
     // ✅ 非托管模式：fromuser_wallets表fetchwalletaddress
-    let wallets = state.user_db.get_user_wallets_with_address(&user_id)
+    let wallets = state.user_db.get_user_wallets_with_address(user_id)
         .await
         .map_err(|e| {
             error!("fetchuserwalletfailed: user_id={}, error={}", user_id, e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "queryuserwalletfailed".to_string(),
-                    code: "DB_ERROR".to_string(),
-                }),
-            )
+            ApiError::new(ApiErrorCode::DbError, "queryuserwalletfailed")
         })?;
 
     // 查找指定wallet
     let wallet_info = wallets.iter()
         .find(|w| w.name == name)
         .ok_or_else(|| {
-            (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "Wallet not found".to_string(),
-                    code: "WALLET_NOT_FOUND".to_string(),
-                }),
-            )
+            ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found")
         })?;
 
-    let wallet_address = wallet_info.address.as_ref()
+    wallet_info.address.clone()
         .ok_or_else(|| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Wallet address not found".to_string(),
-                    code: "WALLET_ADDRESS_MISSING".to_string(),
-                }),
-            )
-        })?;
-
-    // ✅ 非托管模式：直接返回存储的address（不需要解密）
-    info!("✅ 非托管addressquery: wallet={}, address={}, network={}", 
-          name, wallet_address, normalized_network);
-    
-    Ok(Json(AddressResponse {
-        address: wallet_address.clone(),
-        network: normalized_network.to_string(),
-    }))
+            ApiError::new(ApiErrorCode::WalletAddressMissing, "Wallet address not found")
+        })
 }
 
@@ -0,0 +1,121 @@
+//! wallet address book 的 CSV 导入/导出
+//!
+//! 导入边收请求体边解析，不整体缓存；CSV 本身格式错误返回 400
+//! `CSV_MALFORMED`（带行号）。单行内容不合法默认跳过其余照常写入，
+//! `strict=true` 时只要有不合法的行就整体不写（422，响应仍带完整 diff）。
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::authorize_owner_or_admin;
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ValidPath, WalletNameParam};
+use crate::core::address_book::{export_csv, AddressBookImport, ImportCounts, ImportPlan};
+use crate::util::csv::CsvError;
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("address book storage failed: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access address book")
+}
+
+fn malformed(e: CsvError) -> ApiError {
+    ApiError::new(ApiErrorCode::CsvMalformed, e.to_string())
+}
+
+async fn require_wallet(state: &WalletServer, name: &str) -> Result<(), ApiError> {
+    match state.wallet_manager.get_wallet_by_name(name).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found")),
+        Err(e) => {
+            error!("wallet lookup for {} failed: {}", name, e);
+            Err(ApiError::new(ApiErrorCode::DbError, "Failed to load wallet"))
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AddressBookImportQuery {
+    pub dry_run: bool,
+    pub strict: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AddressBookImportResponse {
+    pub dry_run: bool,
+    pub strict: bool,
+    /// 变更已写入；dry-run 或 strict 拒绝时为 false
+    pub applied: bool,
+    pub counts: ImportCounts,
+    #[serde(flatten)]
+    pub plan: ImportPlan,
+}
+
+/// `POST /api/wallets/:name/address_book/import?dry_run=&strict=`：wallet owner 或 admin，请求体为 CSV
+pub async fn import_address_book(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    Query(query): Query<AddressBookImportQuery>,
+    body: Body,
+) -> Result<(StatusCode, Json<AddressBookImportResponse>), ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    require_wallet(&state, name).await?;
+
+    let existing = state.storage.address_book(name).await.map_err(storage_error)?;
+    let mut import = AddressBookImport::new(existing);
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| {
+            ApiError::new(ApiErrorCode::BadRequest, format!("Failed to read request body: {}", e))
+        })?;
+        import.feed(&chunk).map_err(malformed)?;
+    }
+    let plan = import.finish().map_err(malformed)?;
+
+    let rejected = query.strict && !plan.invalid.is_empty();
+    let applied = !query.dry_run && !rejected && !plan.is_noop();
+    if applied {
+        state.storage.upsert_address_book(name, &plan.changes()).await.map_err(storage_error)?;
+    }
+    let counts = plan.counts();
+    if !query.dry_run {
+        let details = serde_json::json!({ "applied": applied, "strict": query.strict, "counts": counts });
+        if let Err(e) = state.storage.log_action(name, "address_book_import", &details.to_string(), None, None).await {
+            error!("failed to audit address_book_import on {}: {}", name, e);
+        }
+    }
+    let status = if rejected { StatusCode::UNPROCESSABLE_ENTITY } else { StatusCode::OK };
+    Ok((status, Json(AddressBookImportResponse { dry_run: query.dry_run, strict: query.strict, applied, counts, plan })))
+}
+
+/// `GET /api/wallets/:name/address_book/export`：规范 CSV，同一 address book 每次导出相同
+pub async fn export_address_book(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Response, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    require_wallet(&state, name).await?;
+    let entries = state.storage.address_book(name).await.map_err(storage_error)?;
+    let disposition = format!("attachment; filename=\"{}-address-book.csv\"", name);
+    Ok((
+        [(header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        export_csv(&entries),
+    )
+        .into_response())
+}
@@ -0,0 +1,51 @@
+//! 无上下文的address识别（客服排查用）
+//!
+//! `GET /api/validate/address?value=...` 返回输入可能属于的每个链族及其规范
+//! 形式和警告，并标出是否为托管wallet：admin（API key）可看到wallet名，读权限
+//! 调用方只得到布尔值。
+
+use axum::{extract::State, http::HeaderMap, response::Json};
+use serde::Serialize;
+use std::sync::Arc;
+
+use super::inspect::{is_admin_caller, managed_wallet};
+use crate::api::server::WalletServer;
+use crate::api::types::{ApiError, ValidateAddressQuery};
+use crate::api::validators::ValidQuery;
+use crate::core::validation::{detect_address, AddressMatch};
+
+#[derive(Debug, Serialize)]
+pub struct DetectedAddress {
+    #[serde(flatten)]
+    pub address: AddressMatch,
+    pub managed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wallet_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AddressValidation {
+    pub value: String,
+    /// 至少识别为一种链的address
+    pub valid: bool,
+    pub matches: Vec<DetectedAddress>,
+}
+
+/// `GET /api/validate/address`
+pub async fn validate_address(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidQuery(query): ValidQuery<ValidateAddressQuery>,
+) -> Result<Json<AddressValidation>, ApiError> {
+    let is_admin = is_admin_caller(&headers, &state).await?;
+    let mut matches = Vec::new();
+    for address in detect_address(&query.value) {
+        let wallet_name = managed_wallet(&state, &address.normalized).await?;
+        matches.push(DetectedAddress {
+            managed: wallet_name.is_some(),
+            wallet_name: wallet_name.filter(|_| is_admin),
+            address,
+        });
+    }
+    Ok(Json(AddressValidation { valid: !matches.is_empty(), value: query.value, matches }))
+}
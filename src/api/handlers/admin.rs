@@ -0,0 +1,446 @@
+//! 运维 (admin) handlers：跨wallet transaction 查询与批量状态复查
+//!
+//! RPC 故障期间运维需要直接查看“所有wallet中超过 10 分钟仍 pending 的transaction”，
+//! 这里只读 transactions 表，不加载任何wallet密文。
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use super::transaction::verify_external_transaction;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::pagination::{PageCursor, PageDirection};
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{NetworkName, ParamError, ValidQuery};
+use crate::blockchain::traits::TransactionStatus;
+use crate::intents::{IntentError, ReconcileReport};
+use crate::ops::jobs::{JobRunError, JobState};
+use crate::security::error_sanitizer::sanitize_error_message;
+use crate::storage::{AuditLog, ProfileRebuildReport, TransactionFilter, TransactionRecord};
+
+/// 单页最大条数
+pub const MAX_ADMIN_PAGE_SIZE: usize = 500;
+const DEFAULT_ADMIN_PAGE_SIZE: usize = 50;
+/// 单次 recheck 最多复查的transaction数
+pub const MAX_RECHECK_BATCH: usize = 200;
+const DEFAULT_RECHECK_BATCH: usize = 100;
+/// recheck 时并发 RPC 请求上限
+pub const RECHECK_CONCURRENCY: usize = 8;
+
+/// 只对超过此时长（秒）未推进的sign意图做对账，避免和进行中的发送抢状态
+pub const DEFAULT_RECONCILE_MIN_AGE_SECS: u64 = 60;
+
+/// 查询条件；`status` / `network` 支持逗号分隔多个值，`older_than` 单位为秒
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AdminTransactionQuery {
+    pub status: Option<String>,
+    pub network: Option<String>,
+    pub wallet_id: Option<String>,
+    pub older_than: Option<u64>,
+    pub newer_than: Option<u64>,
+    pub min_amount: Option<f64>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+/// `POST /api/admin/transactions/recheck` 请求体
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RecheckTransactionsRequest {
+    pub status: Option<String>,
+    pub network: Option<String>,
+    pub wallet_id: Option<String>,
+    pub older_than: Option<u64>,
+    pub newer_than: Option<u64>,
+    pub min_amount: Option<f64>,
+    pub limit: Option<usize>,
+}
+
+/// `POST /api/admin/intents/reconcile` 请求体
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ReconcileIntentsRequest {
+    pub min_age_secs: Option<u64>,
+}
+
+/// `POST /api/admin/wallet-profiles/rebuild` 请求体；不指定 `wallet_id` 时重建全部
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RebuildProfilesRequest {
+    pub wallet_id: Option<String>,
+}
+
+/// `POST /api/admin/transactions/broadcast` 请求体
+#[derive(Debug, Deserialize)]
+pub struct BroadcastRawTransactionRequest {
+    pub network: String,
+    /// `0x` hex，legacy 或类型化（0x01 / 0x02）已sign transaction
+    pub raw_tx: String,
+    /// 接受没有 EIP-155 重放保护的 legacy sign
+    #[serde(default)]
+    pub allow_unprotected: bool,
+}
+
+fn split_list(raw: Option<&str>) -> Vec<String> {
+    raw.map(|s| {
+        s.split(',')
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn build_filter(
+    status: Option<&str>,
+    network: Option<&str>,
+    wallet_id: Option<&str>,
+    older_than: Option<u64>,
+    newer_than: Option<u64>,
+    min_amount: Option<f64>,
+) -> TransactionFilter {
+    let now = chrono::Utc::now();
+    TransactionFilter {
+        statuses: split_list(status),
+        networks: split_list(network),
+        wallet_ids: wallet_id
+            .map(|s| s.split(',').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect())
+            .unwrap_or_default(),
+        created_before: older_than.map(|secs| now - chrono::Duration::seconds(secs as i64)),
+        created_after: newer_than.map(|secs| now - chrono::Duration::seconds(secs as i64)),
+        min_amount,
+    }
+}
+
+pub(super) fn unauthorized() -> ApiError {
+    ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
+}
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("admin transaction query failed: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to query transactions")
+}
+
+/// 链上状态映射为存储状态；Pending/Unknown 不产生状态迁移
+fn stored_status(status: &TransactionStatus) -> Option<&'static str> {
+    match status {
+        TransactionStatus::Confirmed => Some("confirmed"),
+        TransactionStatus::Failed => Some("failed"),
+        TransactionStatus::Pending | TransactionStatus::Unknown => None,
+    }
+}
+
+/// `GET /api/admin/transactions?status=pending&older_than=600&page=1&page_size=50`
+pub async fn admin_transactions(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Query(query): Query<AdminTransactionQuery>,
+) -> Result<Json<AdminTransactionsResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(DEFAULT_ADMIN_PAGE_SIZE).clamp(1, MAX_ADMIN_PAGE_SIZE);
+    let filter = build_filter(
+        query.status.as_deref(),
+        query.network.as_deref(),
+        query.wallet_id.as_deref(),
+        query.older_than,
+        query.newer_than,
+        query.min_amount,
+    );
+
+    let (rows, total) = state
+        .storage
+        .query_transactions(&filter, (page - 1) * page_size, page_size)
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(AdminTransactionsResponse {
+        transactions: rows.into_iter().map(AdminTransactionRecord::from).collect(),
+        total,
+        page,
+        page_size,
+    }))
+}
+
+/// `GET /api/admin/audit-logs` 的过滤条件
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AuditLogQuery {
+    pub wallet_id: Option<String>,
+}
+
+/// 审计日志按自增 id 倒序分页
+#[derive(Debug, Clone, Copy)]
+pub struct AuditLogPages;
+
+impl PageScope for AuditLogPages {
+    const SCOPE: &'static str = "audit_logs";
+    const DIRECTION: PageDirection = PageDirection::Desc;
+    const DEFAULT_LIMIT: usize = DEFAULT_ADMIN_PAGE_SIZE;
+    const MAX_LIMIT: usize = MAX_ADMIN_PAGE_SIZE;
+}
+
+/// `GET /api/admin/audit-logs?wallet_id=treasury&cursor=...&limit=100`
+///
+/// 每行都经过 MAC 校验；任一行校验失败则整页返回 500
+pub async fn list_audit_logs(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Query(query): Query<AuditLogQuery>,
+    ValidQuery(request): ValidQuery<PageRequest<AuditLogPages>>,
+) -> Result<Json<Page<AuditLog>>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let before_id = request.cursor.as_ref().map(|c| c.as_int().ok_or(ParamError::Cursor)).transpose()?;
+    let (logs, next) = state
+        .storage
+        .audit_logs_page(query.wallet_id.as_deref(), before_id, request.limit)
+        .await
+        .map_err(audit_error)?;
+    let next = next.map(|id| PageCursor::int(PageDirection::Desc, id));
+    Ok(Json(request.page(logs, next, None).map_err(audit_error)?))
+}
+
+fn audit_error(e: anyhow::Error) -> ApiError {
+    error!("audit log query failed: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to read audit logs")
+}
+
+enum RecheckOutcome {
+    Unchanged,
+    Transitioned,
+    Error,
+}
+
+async fn recheck_one(state: &WalletServer, tx: TransactionRecord) -> RecheckOutcome {
+    let client = match state.chain_clients.get(&tx.network) {
+        Ok(c) => c,
+        Err(e) => {
+            warn!("recheck: {} skipped: {}", tx.id, e);
+            return RecheckOutcome::Error;
+        }
+    };
+
+    let status = match client.get_transaction_status(&tx.tx_hash).await {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("recheck: status query failed for {}: {}", tx.tx_hash, e);
+            return RecheckOutcome::Error;
+        }
+    };
+
+    let new_status = match stored_status(&status) {
+        Some(s) if s != tx.status => s,
+        _ => return RecheckOutcome::Unchanged,
+    };
+    let confirmed_at = (new_status == "confirmed").then(chrono::Utc::now);
+
+    match state.storage.update_transaction_status(&tx.id, new_status, confirmed_at).await {
+        Ok(()) => {
+            info!("recheck: {} {} -> {}", tx.id, tx.status, new_status);
+            RecheckOutcome::Transitioned
+        }
+        Err(e) => {
+            error!("recheck: failed to persist status for {}: {}", tx.id, e);
+            RecheckOutcome::Error
+        }
+    }
+}
+
+/// `POST /api/admin/transactions/recheck`：按过滤条件重新轮询链上状态
+pub async fn recheck_transactions(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(req): Json<RecheckTransactionsRequest>,
+) -> Result<Json<RecheckTransactionsResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let limit = req.limit.unwrap_or(DEFAULT_RECHECK_BATCH).clamp(1, MAX_RECHECK_BATCH);
+    let filter = build_filter(
+        req.status.as_deref(),
+        req.network.as_deref(),
+        req.wallet_id.as_deref(),
+        req.older_than,
+        req.newer_than,
+        req.min_amount,
+    );
+
+    let (rows, _) = state.storage.query_transactions(&filter, 0, limit).await.map_err(storage_error)?;
+    let checked = rows.len();
+
+    let outcomes: Vec<RecheckOutcome> = stream::iter(rows)
+        .map(|tx| recheck_one(&state, tx))
+        .buffer_unordered(RECHECK_CONCURRENCY)
+        .collect()
+        .await;
+
+    let transitioned = outcomes.iter().filter(|o| matches!(o, RecheckOutcome::Transitioned)).count();
+    let errors = outcomes.iter().filter(|o| matches!(o, RecheckOutcome::Error)).count();
+
+    Ok(Json(RecheckTransactionsResponse { checked, transitioned, errors }))
+}
+
+/// `POST /api/admin/transactions/broadcast`：广播外部sign的原始transaction
+///
+/// 广播前解码并check chain id、重放保护，以及发送方若是托管wallet时 nonce 是否已被
+/// sign意图占用。
+pub async fn broadcast_raw_transaction(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(req): Json<BroadcastRawTransactionRequest>,
+) -> Result<Json<BroadcastRawTransactionResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let network = NetworkName::try_from(req.network.as_str()).and_then(NetworkName::require_evm)?;
+    let network = network.as_str();
+
+    let transaction = verify_external_transaction(&state, network, &req.raw_tx, req.allow_unprotected).await?;
+    let raw = req.raw_tx.trim();
+    let raw = hex::decode(raw.strip_prefix("0x").unwrap_or(raw)).unwrap_or_default();
+    let tx_hash = state.signing_intents.chain().send_raw_transaction(network, raw.into()).await.map_err(|e| {
+        warn!("admin broadcast of {:?} on {} failed: {}", transaction.hash, network, e);
+        ApiError::new(ApiErrorCode::BroadcastFailed, sanitize_error_message(&e.to_string()))
+    })?;
+    info!("admin broadcast {:?} from {:?} on {}", tx_hash, transaction.from, network);
+    Ok(Json(BroadcastRawTransactionResponse { tx_hash: format!("{:?}", tx_hash), transaction }))
+}
+
+/// `POST /api/admin/intents/reconcile`：处理崩溃遗留的sign意图（补记或释放 nonce）
+pub async fn reconcile_intents(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(req): Json<ReconcileIntentsRequest>,
+) -> Result<Json<ReconcileReport>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let min_age = req.min_age_secs.unwrap_or(DEFAULT_RECONCILE_MIN_AGE_SECS);
+    let report = state.signing_intents.reconcile(min_age).await.map_err(|e| {
+        error!("signing intent reconciliation failed: {}", e);
+        let status = match &e {
+            IntentError::Chain(_) => StatusCode::BAD_GATEWAY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError::from_code(status, e.code(), "Failed to reconcile signing intents")
+    })?;
+    info!("intent reconcile: {:?}", report);
+    Ok(Json(report))
+}
+
+/// `POST /api/admin/wallet-profiles/rebuild`：从已确认transaction重新计算wallet行为画像
+///
+/// 增量更新不会撤销后来被回滚的确认，画像疑似漂移时用它校正。
+pub async fn rebuild_wallet_profiles(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(req): Json<RebuildProfilesRequest>,
+) -> Result<Json<ProfileRebuildReport>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let wallet_id = req.wallet_id.as_deref().map(str::trim).filter(|w| !w.is_empty());
+    let report = state.storage.rebuild_wallet_profiles(wallet_id).await.map_err(|e| {
+        error!("wallet profile rebuild failed: {}", e);
+        ApiError::new(ApiErrorCode::DbError, "Failed to rebuild wallet profiles")
+    })?;
+    info!("wallet profile rebuild ({}): {:?}", wallet_id.unwrap_or("all wallets"), report);
+    Ok(Json(report))
+}
+
+/// `GET /api/admin/summary`：实例 id 以及谁持有哪些调度/nonce 租约
+pub async fn admin_summary(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+) -> Result<Json<AdminSummaryResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let instance_id = state.storage.instance_id().to_string();
+    let locks = state.storage.active_locks().await.map_err(|e| {
+        error!("lock query failed: {}", e);
+        ApiError::new(ApiErrorCode::DbError, "Failed to query locks")
+    })?;
+    let locks = locks
+        .into_iter()
+        .map(|lock| LockStatus {
+            held_by_self: lock.holder_id == instance_id,
+            name: lock.name,
+            holder_id: lock.holder_id,
+            expires_at: lock.expires_at,
+            acquired_at: lock.acquired_at,
+        })
+        .collect();
+    let version = super::system_info::collect_version(&state).await;
+    Ok(Json(AdminSummaryResponse {
+        instance_id,
+        multi_instance: state.config.cluster.multi_instance,
+        locks,
+        jobs: state.jobs.states(),
+        wallet_cache: state.storage.wallet_cache_stats(),
+        build: version.build,
+        runtime: version.runtime,
+    }))
+}
+
+/// `GET /api/admin/jobs`：各后台任务最近一次运行、成功、错误与连续失败次数
+pub async fn list_jobs(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+) -> Result<Json<JobsResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let jobs = state.jobs.states();
+    Ok(Json(JobsResponse { healthy: jobs.iter().all(|job| job.healthy), jobs }))
+}
+
+/// `POST /api/admin/jobs/:name/run`：立即运行一次（不等调度），返回运行后的状态
+pub async fn run_job(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Result<Json<JobState>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let job = state.jobs.run_now(&name).await.map_err(|e| {
+        let (status, code) = match &e {
+            JobRunError::NotFound(_) => (StatusCode::NOT_FOUND, "JOB_NOT_FOUND"),
+            JobRunError::AlreadyRunning(_) => (StatusCode::CONFLICT, "JOB_RUNNING"),
+            JobRunError::Maintenance => (StatusCode::CONFLICT, "MAINTENANCE_MODE"),
+            JobRunError::ShuttingDown => (StatusCode::SERVICE_UNAVAILABLE, "SHUTTING_DOWN"),
+        };
+        ApiError::from_code(status, code, e.to_string())
+    })?;
+    info!("job {} run manually: {:?}", name, job);
+    Ok(Json(job))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_filter_parses_lists() {
+        let f = build_filter(Some("Pending, failed,"), Some("eth"), None, Some(600), None, Some(1.5));
+        assert_eq!(f.statuses, vec!["pending".to_string(), "failed".to_string()]);
+        assert_eq!(f.networks, vec!["eth".to_string()]);
+        assert!(f.wallet_ids.is_empty());
+        assert!(f.created_before.unwrap() < chrono::Utc::now() - chrono::Duration::seconds(599));
+        assert!(f.created_after.is_none());
+        assert_eq!(f.min_amount, Some(1.5));
+    }
+
+    #[test]
+    fn test_stored_status_mapping() {
+        assert_eq!(stored_status(&TransactionStatus::Confirmed), Some("confirmed"));
+        assert_eq!(stored_status(&TransactionStatus::Failed), Some("failed"));
+        assert_eq!(stored_status(&TransactionStatus::Pending), None);
+        assert_eq!(stored_status(&TransactionStatus::Unknown), None);
+    }
+}
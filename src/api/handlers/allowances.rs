@@ -0,0 +1,143 @@
+//! ERC-20 授权 handlers
+//!
+//! `GET /api/wallets/:name/allowance` 经链客户端 eth_call `allowance(owner, spender)`；
+//! `POST /api/wallets/:name/approve` 由服务端解锁wallet密钥，sign并广播
+//! `approve(spender, amount)`。数额一律是最小单位的整数，`0` 即撤销授权。
+
+use axum::{extract::State, http::HeaderMap, response::Json};
+use ethers::signers::Signer;
+use ethers::types::{Address, U256};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::address::stored_wallet_address;
+use super::deadman::unlock_error;
+use super::key_usage::authorize_signing;
+use super::transaction::send_call_with_signer;
+use super::wallet_networks::check_network_allowed;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::wallet_scope::extract_wallet_caller;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ValidJson, ValidPath, ValidQuery, WalletNameParam};
+use crate::blockchain::erc20::{preflight, Erc20Error};
+use crate::core::domain::Tx;
+use crate::core::errors::WalletError;
+use crate::intents::DecisionContext;
+use crate::storage::WalletCapability;
+
+/// `GET /api/wallets/:name/allowance?network=&token=&spender=`
+pub async fn get_allowance(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(query): ValidQuery<AllowanceParams>,
+) -> Result<Json<AllowanceResponse>, ApiError> {
+    let caller = extract_wallet_caller(&headers, &state).await?;
+    let name = name.as_str();
+    caller.authorize(&state, name, WalletCapability::ReadBalance).await?;
+    caller.audit(&state, name, "read_allowance").await;
+
+    let network = query.network.as_str();
+    check_network_allowed(&state, name, network)?;
+    let owner = stored_wallet_address(&state, caller.user_id(), name).await?;
+
+    let client =
+        state.chain_clients.get(network).map_err(|e| ApiError::new(ApiErrorCode::NetworkUnavailable, e.to_string()))?;
+    let allowance = client
+        .get_erc20_allowance(&owner, query.spender.as_str(), query.token.as_str())
+        .await
+        .map_err(|e| token_call_error(network, &query.token, e))?;
+
+    Ok(Json(AllowanceResponse {
+        network: network.to_string(),
+        token: query.token.to_string(),
+        owner,
+        spender: query.spender.to_string(),
+        allowance: allowance.to_string(),
+    }))
+}
+
+/// `POST /api/wallets/:name/approve`：先以 eth_call 模拟，会 revert 或返回 false
+/// 的授权不上链；节点查询失败时与 bundle 的 approve 步骤一样跳过模拟
+///
+/// 授权不动用原生币，支出上限与审查阈值管不到它；无限授权因此须由请求带上
+/// `allow_unlimited` 确认，否则返回 422 `UNLIMITED_APPROVAL_UNCONFIRMED`，不解锁wallet。
+pub async fn approve_token(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<ApproveToken>,
+) -> Result<Json<ApproveTokenResponse>, ApiError> {
+    let caller = extract_wallet_caller(&headers, &state).await?;
+    let name = name.as_str();
+    caller.authorize(&state, name, WalletCapability::Send).await?;
+    caller.audit(&state, name, "approve").await;
+    let mut decision = DecisionContext::new();
+    decision.record_requester(caller.user_id());
+    caller.record_token_limit(&mut decision);
+
+    let network = payload.network.as_str();
+    let allowed = check_network_allowed(&state, name, network)?;
+    decision.record_network(network, allowed);
+
+    let unlimited = payload.amount.value() == U256::MAX;
+    if unlimited && !payload.allow_unlimited {
+        warn!(
+            "refused unconfirmed unlimited approval: wallet={}, token={}, spender={}",
+            name, payload.token, payload.spender
+        );
+        return Err(ApiError::new(
+            ApiErrorCode::UnlimitedApprovalUnconfirmed,
+            format!("Approving {} for an unlimited amount of {} must be confirmed", payload.spender, payload.token),
+        ));
+    }
+    let data = Tx::encode_erc20_approve_calldata(payload.spender.as_str(), payload.amount.as_str())
+        .map_err(|e| ApiError::new(ApiErrorCode::InvalidInput, e.to_string()))?;
+    // 两者均已validate
+    let token: Address =
+        payload.token.as_str().parse().map_err(|_| ApiError::new(ApiErrorCode::InvalidAddress, "Invalid token"))?;
+
+    let signer =
+        state.wallet_manager.ethereum_signer(name, &payload.password).await.map_err(|e| unlock_error(name, e))?;
+    let behavior = state.tokens.behavior(network, token).await;
+    if behavior.max_approval_disallowed && unlimited {
+        return Err(ApiError::new(
+            ApiErrorCode::InvalidAmount,
+            format!("Token {} does not allow unlimited approvals; approve the exact amount", payload.token),
+        ));
+    }
+    match preflight(state.gas_oracle.as_ref(), network, signer.address(), token, data.clone().into(), &behavior).await {
+        Ok(()) => {}
+        Err(Erc20Error::Rpc(e)) => warn!("approve preflight on {} skipped: {}", network, e),
+        Err(e) => return Err(token_call_error(network, &payload.token, e)),
+    }
+
+    authorize_signing(&state, name).await?;
+    let tx_hash =
+        send_call_with_signer(&state, name, network, &signer, token, U256::zero(), data.into(), Some(decision)).await?;
+    info!("approve sent: wallet={}, network={}, token={}, spender={}", name, network, payload.token, payload.spender);
+
+    Ok(Json(ApproveTokenResponse {
+        tx_hash,
+        network: network.to_string(),
+        token: payload.token.to_string(),
+        spender: payload.spender.to_string(),
+        amount: payload.amount.to_string(),
+    }))
+}
+
+/// 合约 revert 或返回值不合 ERC-20（例如address上没有合约）是 422，不是 500
+fn token_call_error(network: &str, token: &impl std::fmt::Display, e: Erc20Error) -> ApiError {
+    warn!("ERC-20 token call failed: network={}, token={}, error={}", network, token, e);
+    let code = match &e {
+        Erc20Error::Reverted(_) => ApiErrorCode::TokenCallReverted,
+        Erc20Error::MalformedUint(..)
+        | Erc20Error::MalformedReturn(_)
+        | Erc20Error::ReturnedFalse
+        | Erc20Error::NoReturnData => ApiErrorCode::TokenBadReturnData,
+        Erc20Error::Rpc(WalletError::NetworkBusy(_)) => ApiErrorCode::NetworkBusy,
+        Erc20Error::Rpc(_) => ApiErrorCode::BalanceCheckFailed,
+    };
+    ApiError::new(code, e.to_string())
+}
@@ -0,0 +1,110 @@
+//! 手续费分析（API key）
+//!
+//! 数据来自 `fee_history`：发送广播时写入报价，`ops::fee_tracking` 拿到回执后
+//! 补全 gas used 与实际价格；市场均价来自 gas oracle 的定时采样。
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+use super::admin::unauthorized;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{NetworkName, ParamError, Validate, ValidQuery};
+use crate::storage::FeeGrouping;
+
+/// 最长统计窗口（天）
+pub const MAX_FEE_WINDOW_DAYS: i64 = 365;
+const DEFAULT_FEE_WINDOW: &str = "30d";
+
+#[derive(Deserialize)]
+pub struct FeeAnalyticsQuery {
+    pub network: String,
+    /// `<n>h` 或 `<n>d`，默认 30d
+    pub window: Option<String>,
+    #[serde(default)]
+    pub group_by: FeeGrouping,
+}
+
+/// [`FeeAnalyticsQuery`] validate后
+pub struct FeeAnalyticsParams {
+    pub network: NetworkName,
+    pub window_secs: i64,
+    pub group_by: FeeGrouping,
+}
+
+/// `"24h"` / `"30d"` → seconds
+fn parse_window(window: &str) -> Result<i64, ParamError> {
+    let (count, unit_secs) = if let Some(hours) = window.strip_suffix('h') {
+        (hours, 3600)
+    } else if let Some(days) = window.strip_suffix('d') {
+        (days, 86_400)
+    } else {
+        return Err(ParamError::Window(MAX_FEE_WINDOW_DAYS));
+    };
+    count
+        .parse::<i64>()
+        .ok()
+        .and_then(|n| n.checked_mul(unit_secs))
+        .filter(|secs| *secs > 0 && *secs <= MAX_FEE_WINDOW_DAYS * 86_400)
+        .ok_or(ParamError::Window(MAX_FEE_WINDOW_DAYS))
+}
+
+impl Validate for FeeAnalyticsParams {
+    type Raw = FeeAnalyticsQuery;
+
+    fn validate(raw: FeeAnalyticsQuery) -> Result<Self, ParamError> {
+        Ok(Self {
+            network: NetworkName::try_from(raw.network.as_str())?,
+            window_secs: parse_window(raw.window.as_deref().unwrap_or(DEFAULT_FEE_WINDOW))?,
+            group_by: raw.group_by,
+        })
+    }
+}
+
+/// `GET /api/analytics/fees?network=eth&window=30d&group_by=hour|day|week`
+pub async fn fee_analytics(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidQuery(query): ValidQuery<FeeAnalyticsParams>,
+) -> Result<Json<FeeAnalyticsResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let network = query.network.as_str();
+    let since = Utc::now() - chrono::Duration::seconds(query.window_secs);
+
+    let (buckets, totals) = state.storage.fee_analytics(network, since, query.group_by).await.map_err(|e| {
+        error!("fee analytics query failed: network={}, error={}", network, e);
+        ApiError::new(ApiErrorCode::DbError, "Failed to load fee analytics")
+    })?;
+
+    Ok(Json(FeeAnalyticsResponse {
+        network: network.to_string(),
+        window_secs: query.window_secs,
+        group_by: query.group_by,
+        totals,
+        buckets,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(parse_window("24h").unwrap(), 86_400);
+        assert_eq!(parse_window("30d").unwrap(), 30 * 86_400);
+        assert_eq!(parse_window("365d").unwrap(), 365 * 86_400);
+        for bad in ["", "d", "0d", "-1d", "30", "30w", "366d", "99999999999999999d"] {
+            assert!(matches!(parse_window(bad), Err(ParamError::Window(_))), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! 管理 API 密钥轮换 handler（API key）
+//!
+//! `POST /api/admin/rotate_api_key` 把第二把密钥升为主密钥并生成新的第二把。新密钥
+//! 只出现在这次响应里，不写日志也不入库；审计日志只记两把密钥的指纹。
+//!
+//! 安装了密钥后端时，新的 `API_KEY` / `API_KEY_SECONDARY` 先写回后端再在本进程生效，
+//! 重启后仍然有效，其他实例由 `ops::secret_refresh` 在下一次刷新时换上；写入失败时
+//! 旧密钥不变。环境变量后端无法写回：轮换只作用于本进程，`cluster.multi_instance`
+//! 时拒绝轮换，以免各实例接受的密钥不一致。
+
+use axum::{extract::State, http::HeaderMap, response::Json};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::require_api_key;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::security::env_manager::secret_backend;
+
+/// 轮换的审计记录挂在这个 wallet_id 下
+const AUDIT_ID: &str = "system";
+
+fn unauthorized() -> ApiError {
+    ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
+}
+
+fn not_configured() -> ApiError {
+    ApiError::new(ApiErrorCode::ApiKeyNotConfigured, "No admin API key is configured; authentication is disabled")
+}
+
+/// `POST /api/admin/rotate_api_key`：主密钥或第二把均可调用，DEV_MODE 下同样要带密钥。
+/// 读密钥、写回后端、本进程换上都在轮换锁内，并发的轮换依次进行
+pub async fn rotate_api_key(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+) -> Result<Json<ApiKeyRotationResponse>, ApiError> {
+    if state.api_key.is_none() {
+        return Err(not_configured());
+    }
+    require_api_key(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let _rotation = state.api_key.lock_rotation().await;
+    let (keys, rotated) = state.api_key.next_rotation().ok_or_else(not_configured)?;
+    match secret_backend::installed() {
+        Some(store) => store.store_api_keys(&keys).await.map_err(|e| {
+            error!("failed to save the rotated API key: {}", e);
+            ApiError::new(ApiErrorCode::ApiKeySaveFailed, format!("The rotated API key was not saved: {}", e))
+        })?,
+        None if state.config.cluster.multi_instance => {
+            return Err(ApiError::new(
+                ApiErrorCode::ApiKeyRotationNotShared,
+                "cluster.multi_instance needs a writable secret backend to rotate the API key",
+            ))
+        }
+        None => warn!("API key rotated in this process only; update API_KEY / API_KEY_SECONDARY before restarting"),
+    }
+    state.api_key.replace(keys);
+
+    info!(
+        "admin API key rotated: primary {}, new secondary {}",
+        rotated.primary_fingerprint, rotated.secondary_fingerprint
+    );
+    let details = serde_json::json!({
+        "primary_fingerprint": rotated.primary_fingerprint,
+        "secondary_fingerprint": rotated.secondary_fingerprint,
+    })
+    .to_string();
+    if let Err(e) = state.storage.log_action(AUDIT_ID, "api_key.rotated", &details, None, None).await {
+        error!("failed to audit API key rotation: {}", e);
+    }
+
+    Ok(Json(ApiKeyRotationResponse {
+        secondary_api_key: rotated.secondary.to_string(),
+        primary_fingerprint: rotated.primary_fingerprint,
+        secondary_fingerprint: rotated.secondary_fingerprint,
+    }))
+}
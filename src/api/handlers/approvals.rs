@@ -0,0 +1,244 @@
+//! 四眼审批 handlers
+//!
+//! 超过wallet审查阈值的服务端sign发送会被挂起（见 `transaction` handlers），
+//! 由 `approvals.approvers` 中的另一人批准或拒绝。批准时重新validate请求方
+//! 权限与 token 上限、wallet 网络白名单、重复发送和余额，通过后才走正常的
+//! sign与广播路径，异常检测也在那里执行。
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use ethers::types::{Address, U256};
+use ethers::utils::format_ether;
+use std::sync::Arc;
+use tracing::{error, warn};
+
+use super::admin::unauthorized;
+use super::key_usage::authorize_signing;
+use super::transaction::{check_duplicate, check_spending_limit, send_with_signer};
+use super::wallet_networks::check_network_allowed;
+use super::wallet_tokens::ADMIN_ISSUER;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::extract_user::{authorize_owner_or_admin, extract_user_id_from_token};
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::middleware::wallet_scope::WalletCaller;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{Amount, ValidJson, ValidPath, ValidQuery, WalletNameParam};
+use crate::approvals::ApprovalError;
+use crate::blockchain::gas_oracle::min_transfer_cost;
+use crate::intents::DecisionContext;
+use crate::storage::{ApprovalRecord, WalletCapability};
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("approval storage error: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access approvals")
+}
+
+pub(crate) fn approval_error(e: ApprovalError) -> ApiError {
+    let status = match &e {
+        ApprovalError::NotFound(_) => StatusCode::NOT_FOUND,
+        ApprovalError::NotPending { .. } | ApprovalError::SignerUnavailable => StatusCode::CONFLICT,
+        ApprovalError::Expired { .. } => StatusCode::GONE,
+        ApprovalError::SelfApproval | ApprovalError::NotApprover => StatusCode::FORBIDDEN,
+        ApprovalError::Storage(inner) => {
+            error!("approval storage error: {}", inner);
+            return ApiError::from_code(StatusCode::INTERNAL_SERVER_ERROR, e.code(), "Failed to access approvals");
+        }
+    };
+    ApiError::from_code(status, e.code(), e.to_string())
+}
+
+/// 会话user，且在 `approvals.approvers` 名单中（按 User ID 或邮箱）
+async fn approver(headers: &HeaderMap, state: &Arc<WalletServer>) -> Result<String, ApiError> {
+    let user_id = extract_user_id_from_token(headers, state).await?;
+    let email = match state.user_db.get_user_by_id(&user_id).await {
+        Ok(user) => Some(user.email),
+        Err(e) => {
+            warn!("approver lookup failed for {}: {}", user_id, e);
+            None
+        }
+    };
+    if !state.approvals.is_approver(&user_id, email.as_deref()) {
+        return Err(approval_error(ApprovalError::NotApprover));
+    }
+    Ok(user_id)
+}
+
+/// `GET /api/approvals?status=pending`：审批人会话或 API key
+pub async fn list_approvals(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidQuery(query): ValidQuery<ApprovalListParams>,
+) -> Result<Json<ApprovalListResponse>, ApiError> {
+    if authenticate(&headers, &state.api_key).await.is_err() {
+        approver(&headers, &state).await?;
+    }
+    let approvals = state.approvals.list(query.status, query.limit).await.map_err(approval_error)?;
+    Ok(Json(ApprovalListResponse { approvals }))
+}
+
+/// `POST /api/approvals/:id/approve`
+///
+/// 重新validate失败（余额不足、权限已撤销等）时请求保持 `pending`；
+/// sign或广播失败时标记为 `failed`。
+pub async fn approve_approval(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ApprovalDecisionResponse>, ApiError> {
+    let approver = approver(&headers, &state).await?;
+    let record = state.approvals.check(&id, &approver).await.map_err(approval_error)?;
+    // 重新check当日额度到写入transaction记录期间不让同一wallet的其他发送插入
+    let _sends = state.storage.lock_sends(&record.wallet_name, &record.network).await;
+    let (to, value, mut decision) = revalidate(&state, &record).await?;
+
+    let signer = state.approvals.claim(&record, &approver).await.map_err(approval_error)?;
+    let record = state.approvals.get(&id).await.map_err(approval_error)?;
+    decision.record_approval(&record.id, &record.requested_by, &approver);
+    let sent = match authorize_signing(&state, &record.wallet_name).await {
+        Ok(()) => {
+            send_with_signer(&state, &record.wallet_name, &record.network, &signer, to, value, Some(decision)).await
+        }
+        Err(e) => Err(e),
+    };
+    let tx_hash = match sent {
+        Ok(tx_hash) => tx_hash,
+        Err(err) => {
+            if let Err(e) = state.approvals.failed(&record, &approver, err.message()).await {
+                error!("failed to mark approval {} as failed: {}", id, e);
+            }
+            return Err(err);
+        }
+    };
+    if let Err(e) = state.approvals.executed(&record, &tx_hash).await {
+        // 已广播，不能再让调用方重试
+        error!("approval {} broadcast as {} but could not be updated: {}", id, tx_hash, e);
+    }
+
+    let approval = state.approvals.get(&id).await.map_err(approval_error)?;
+    Ok(Json(ApprovalDecisionResponse {
+        explorer_url: state.config.blockchain.explorer_tx_url(&approval.network, &tx_hash),
+        approval,
+    }))
+}
+
+/// `POST /api/approvals/:id/reject`，body `{"reason": "..."}`
+pub async fn reject_approval(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    ValidJson(payload): ValidJson<RejectApproval>,
+) -> Result<Json<ApprovalDecisionResponse>, ApiError> {
+    let approver = approver(&headers, &state).await?;
+    let approval = state.approvals.reject(&id, &approver, &payload.reason).await.map_err(approval_error)?;
+    Ok(Json(ApprovalDecisionResponse { approval, explorer_url: None }))
+}
+
+/// 批准前重新validate挂起的发送；返回 `(to, value)` 与这些check的决策上下文
+async fn revalidate(
+    state: &Arc<WalletServer>,
+    record: &ApprovalRecord,
+) -> Result<(Address, U256, DecisionContext), ApiError> {
+    let corrupt = || storage_error(anyhow::anyhow!("approval {} has an unreadable payload", record.id));
+    let to: Address = record.payload.to.parse().map_err(|_| corrupt())?;
+    let value = U256::from_dec_str(&record.payload.value).map_err(|_| corrupt())?;
+    let mut decision = DecisionContext::new();
+    decision.record_requester(&record.requested_by);
+
+    // 挂起期间网络白名单可能已收紧
+    let allowed = check_network_allowed(state, &record.wallet_name, &record.network)?;
+    decision.record_network(&record.network, allowed);
+
+    // 请求方仍有权从该wallet发送（API key 请求无需再查）
+    if record.requested_by != ADMIN_ISSUER {
+        let caller = match &record.token_id {
+            None => WalletCaller::User(record.requested_by.clone()),
+            Some(token_id) => {
+                let now = state.storage.clock().now().timestamp();
+                let token = state
+                    .storage
+                    .wallet_tokens_for(&record.requested_by, &record.wallet_name)
+                    .await
+                    .map_err(storage_error)?
+                    .into_iter()
+                    .find(|t| &t.id == token_id && t.revoked_at.is_none() && !t.is_expired(now))
+                    .ok_or_else(|| {
+                        ApiError::new(
+                            ApiErrorCode::RequesterNotAuthorized,
+                            "The token this send was requested with is no longer valid",
+                        )
+                    })?;
+                WalletCaller::Token(token)
+            }
+        };
+        caller.authorize(state, &record.wallet_name, WalletCapability::Send).await?;
+        caller.check_amount(&Amount::try_from(record.amount.as_str())?)?;
+        caller.record_token_limit(&mut decision);
+    }
+
+    let allow_duplicate = record.payload.allow_duplicate;
+    check_duplicate(state, &record.wallet_name, &record.network, to, value, allow_duplicate, &mut decision).await?;
+    // 挂起期间可能已有其他发送计入当日额度
+    check_spending_limit(state, &record.wallet_name, &record.network, value, 0, &mut decision).await?;
+
+    // 余额在挂起期间可能已被转走
+    if state.gas_oracle.supports(&record.network) {
+        let network_error = |e: crate::core::errors::WalletError| {
+            warn!("balance re-check for approval {} failed: {}", record.id, e);
+            ApiError::new(ApiErrorCode::NetworkError, "Failed to re-check the wallet balance")
+        };
+        let gas_price = state.gas_oracle.gas_price(&record.network).await.map_err(network_error)?;
+        let balance =
+            state.gas_oracle.native_balance(&record.network, &record.payload.from).await.map_err(network_error)?;
+        let needed = value.saturating_add(min_transfer_cost(gas_price));
+        if balance < needed {
+            return Err(ApiError::new(
+                ApiErrorCode::InsufficientFunds,
+                format!(
+                    "Insufficient balance: {} available, at least {} needed",
+                    format_ether(balance),
+                    format_ether(needed)
+                ),
+            ));
+        }
+    }
+    Ok((to, value, decision))
+}
+
+/// `GET /api/wallets/:name/review_threshold`：wallet owner 或 admin
+pub async fn get_review_threshold(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<ReviewThresholdResponse>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    let threshold = state.storage.review_threshold(name).await.map_err(storage_error)?;
+    Ok(Json(ReviewThresholdResponse { wallet_name: name.to_string(), threshold }))
+}
+
+/// `PUT /api/wallets/:name/review_threshold`：仅 admin（API key），
+/// 避免 owner 自行取消审批要求
+pub async fn put_review_threshold(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<ReviewThreshold>,
+) -> Result<Json<ReviewThresholdResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let name = name.as_str();
+    let threshold = payload.threshold.as_ref().map(|t| t.as_str());
+    state.storage.set_review_threshold(name, threshold, ADMIN_ISSUER).await.map_err(storage_error)?;
+
+    let details = serde_json::json!({ "review_threshold": threshold, "updated_by": ADMIN_ISSUER });
+    if let Err(e) = state.storage.log_action(name, "review_threshold.updated", &details.to_string(), None, None).await
+    {
+        error!("failed to audit review threshold change on {}: {}", name, e);
+    }
+    Ok(Json(ReviewThresholdResponse { wallet_name: name.to_string(), threshold: threshold.map(str::to_string) }))
+}
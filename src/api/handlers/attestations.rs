@@ -0,0 +1,258 @@
+//! 地址控制权证明（attestation）
+//!
+//! 交易所或对手方给出 challenge（或由我们生成，包含address、时间、nonce 与
+//! 可选 audience），用wallet密钥按 EIP-191 或 Bitcoin 消息sign，存档后返回可
+//! 携带的 JSON 文档。`POST /api/attestations/verify` 对任意文档恢复签名者
+//! address，并结合存档判断是否过期或已吊销。
+
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Json,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rand::RngCore;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::error;
+
+use super::deadman::unlock_error;
+use super::inspect::{is_admin_caller, managed_wallet};
+use super::key_usage::authorize_signing;
+use super::wallet_networks::check_network_allowed;
+use super::wallet_tokens::ADMIN_ISSUER;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::authorize_owner_or_admin;
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ValidJson, ValidPath, WalletNameParam};
+use crate::core::errors::WalletError;
+use crate::crypto::message_signing::{eip191_recover, MessageScheme};
+use crate::storage::{AttestationRecord, NewAttestation};
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("attestation storage failed: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access attestations")
+}
+
+fn rfc3339(secs: i64) -> String {
+    Utc.timestamp_opt(secs, 0).single().map(|t| t.to_rfc3339()).unwrap_or_else(|| secs.to_string())
+}
+
+/// 未提供 challenge 时生成；每行一个字段，便于对方人工核对
+fn generate_challenge(
+    address: &str,
+    network: &str,
+    audience: Option<&str>,
+    issued_at: DateTime<Utc>,
+    expires_at: Option<i64>,
+) -> String {
+    let mut nonce = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let mut lines = vec![
+        "Address ownership attestation".to_string(),
+        format!("Address: {}", address),
+        format!("Network: {}", network),
+    ];
+    if let Some(audience) = audience {
+        lines.push(format!("Audience: {}", audience));
+    }
+    lines.push(format!("Issued at: {}", issued_at.to_rfc3339()));
+    if let Some(expires_at) = expires_at {
+        lines.push(format!("Expires at: {}", rfc3339(expires_at)));
+    }
+    lines.push(format!("Nonce: {}", hex::encode(nonce)));
+    lines.join("\n")
+}
+
+async fn audit(state: &WalletServer, name: &str, action: &str, details: serde_json::Value) {
+    if let Err(e) = state.storage.log_action(name, action, &details.to_string(), None, None).await {
+        error!("failed to audit {} on {}: {}", action, name, e);
+    }
+}
+
+/// `POST /api/wallets/:name/attestations`：wallet owner 或 admin，需要wallet Password
+pub async fn create_attestation(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<AttestationConfig>,
+) -> Result<Json<AttestationDocument>, ApiError> {
+    let name = name.as_str();
+    let user_id = authorize_owner_or_admin(&headers, &state, name).await?;
+    let network = payload.network.as_ref().map_or("eth", |n| n.as_str());
+    check_network_allowed(&state, name, network)?;
+
+    let signer =
+        state.wallet_manager.message_signer(name, &payload.password, network).await.map_err(|e| unlock_error(name, e))?;
+    authorize_signing(&state, name).await?;
+
+    let now = state.storage.clock().now();
+    let expires_at = payload.expires_in_secs.map(|ttl| now.timestamp() + ttl as i64);
+    let challenge = payload.challenge.unwrap_or_else(|| {
+        generate_challenge(signer.address(), network, payload.audience.as_deref(), now, expires_at)
+    });
+    let signature = signer.sign(challenge.as_bytes()).map_err(|e| {
+        error!("signing attestation for {} failed: {}", name, e);
+        ApiError::new(ApiErrorCode::SigningFailed, "Failed to sign attestation")
+    })?;
+
+    let record = state
+        .storage
+        .record_attestation(&NewAttestation {
+            wallet_name: name,
+            network,
+            scheme: signer.scheme().as_str(),
+            address: signer.address(),
+            challenge: &challenge,
+            signature: &signature,
+            audience: payload.audience.as_deref(),
+            expires_at,
+        })
+        .await
+        .map_err(storage_error)?;
+    audit(
+        &state,
+        name,
+        "attestation.created",
+        serde_json::json!({
+            "attestation_id": record.id,
+            "network": network,
+            "address": record.address,
+            "audience": record.audience,
+            "by": user_id.as_deref().unwrap_or(ADMIN_ISSUER),
+        }),
+    )
+    .await;
+    Ok(Json((&record).into()))
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttestationListResponse {
+    pub attestations: Vec<AttestationRecord>,
+}
+
+/// `GET /api/wallets/:name/attestations`：存档（含已过期与已吊销），最新在前
+pub async fn list_attestations(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<AttestationListResponse>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    let attestations = state.storage.attestations_for(name).await.map_err(storage_error)?;
+    Ok(Json(AttestationListResponse { attestations }))
+}
+
+/// `POST /api/wallets/:name/attestations/:id/revoke`：吊销后validate一律失败
+pub async fn revoke_attestation(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((name, id)): Path<(String, String)>,
+    ValidJson(payload): ValidJson<RevokeAttestation>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let name = WalletNameParam::try_from(name.as_str())?;
+    let name = name.as_str();
+    let user_id = authorize_owner_or_admin(&headers, &state, name).await?;
+    if !state.storage.revoke_attestation(name, &id, payload.reason.as_deref()).await.map_err(storage_error)? {
+        return Err(ApiError::new(ApiErrorCode::AttestationNotFound, "Attestation not found"));
+    }
+    audit(
+        &state,
+        name,
+        "attestation.revoked",
+        serde_json::json!({
+            "attestation_id": id,
+            "reason": payload.reason,
+            "by": user_id.as_deref().unwrap_or(ADMIN_ISSUER),
+        }),
+    )
+    .await;
+    Ok(Json(serde_json::json!({ "id": id, "revoked": true })))
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttestationVerification {
+    /// 签名有效、address一致、未过期且未吊销
+    pub valid: bool,
+    pub signature_valid: bool,
+    pub recovered_address: Option<String>,
+    pub address_matches: bool,
+    pub expired: bool,
+    pub revoked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<i64>,
+    /// 本服务签发并存档过
+    pub known: bool,
+    /// 恢复出的address属于托管wallet；wallet名只对 admin 返回
+    pub managed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wallet_name: Option<String>,
+}
+
+/// 签名者address；签名无法恢复时为 `None`
+fn recover_signer(document: &AttestationDocument) -> Result<Option<String>, ApiError> {
+    let scheme: MessageScheme = document.scheme.parse().map_err(|e: WalletError| {
+        ApiError::new(ApiErrorCode::InvalidAttestation, e.to_string())
+    })?;
+    let challenge = document.challenge.as_bytes();
+    Ok(match scheme {
+        MessageScheme::Eip191 => eip191_recover(challenge, &document.signature)
+            .ok()
+            .map(|address| ethers::utils::to_checksum(&address, None)),
+        #[cfg(feature = "bitcoin")]
+        MessageScheme::Bip137 => {
+            crate::crypto::message_signing::bitcoin_recover_address(challenge, &document.signature).ok()
+        }
+        #[cfg(not(feature = "bitcoin"))]
+        MessageScheme::Bip137 => None,
+    })
+}
+
+/// `POST /api/attestations/verify`：admin、会话user或带 `read_history` 的wallet token
+pub async fn verify_attestation(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidJson(PresentedAttestation(document)): ValidJson<PresentedAttestation>,
+) -> Result<Json<AttestationVerification>, ApiError> {
+    let is_admin = is_admin_caller(&headers, &state).await?;
+    let recovered_address = recover_signer(&document)?;
+    // EVM address大小写无关；Bitcoin base58 区分大小写
+    let address_matches = recovered_address.as_deref().is_some_and(|recovered| {
+        if recovered.starts_with("0x") {
+            recovered.eq_ignore_ascii_case(&document.address)
+        } else {
+            recovered == document.address
+        }
+    });
+
+    // 存档优先：过期时间以签发时记录为准，吊销列表按签名查
+    let record = state.storage.attestation_by_signature(&document.signature).await.map_err(storage_error)?;
+    let now = state.storage.clock().now().timestamp();
+    let expired = match &record {
+        Some(record) => record.is_expired(now),
+        None => document.expires_at.is_some_and(|at| at <= now),
+    };
+    let revoked_at = record.as_ref().and_then(|r| r.revoked_at);
+
+    let wallet_name = match &recovered_address {
+        Some(address) if address.starts_with("0x") => managed_wallet(&state, &address.to_lowercase()).await?,
+        Some(address) => managed_wallet(&state, address).await?,
+        None => None,
+    };
+    let signature_valid = recovered_address.is_some();
+    Ok(Json(AttestationVerification {
+        valid: signature_valid && address_matches && !expired && revoked_at.is_none(),
+        signature_valid,
+        recovered_address,
+        address_matches,
+        expired,
+        revoked: revoked_at.is_some(),
+        revoked_at,
+        known: record.is_some(),
+        managed: wallet_name.is_some(),
+        wallet_name: wallet_name.filter(|_| is_admin),
+    }))
+}
@@ -0,0 +1,99 @@
+//! 链上历史回填 handlers
+//!
+//! 导入已有资金的wallet后，`POST` 为它在某个network上的地址建立扫描游标（从
+//! `from_block` 到当前链头），后台任务 [`BACKFILL_JOB`] 分批读取并写入账目与
+//! transaction记录；重复请求或中断后重跑都不会产生重复记录。`GET .../status`
+//! 返回各游标进度与按币种汇总的账目。
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use std::sync::Arc;
+use tracing::{debug, error};
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::authorize_owner_or_admin;
+use crate::api::middleware::request_metrics::{note_storage_error, note_wallet_error};
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ValidPath, ValidQuery, WalletNameParam};
+use crate::core::errors::WalletError;
+use crate::ops::backfill::{BackfillError, BACKFILL_JOB};
+use crate::ops::incidents::spawn_named;
+use crate::ops::jobs::JobRunError;
+
+fn backfill_error(e: BackfillError) -> ApiError {
+    let (code, message) = match &e {
+        BackfillError::NotRegistered(..) => (ApiErrorCode::NetworkNotRegistered, e.to_string()),
+        BackfillError::Chain(inner @ WalletError::NetworkBusy(_)) => {
+            note_wallet_error(inner);
+            (ApiErrorCode::NetworkBusy, "RPC quota exhausted, retry later".to_string())
+        }
+        BackfillError::Chain(inner) => {
+            error!("backfill head lookup failed: {}", inner);
+            note_wallet_error(inner);
+            (ApiErrorCode::ChainUnavailable, "Failed to read the chain head".to_string())
+        }
+        BackfillError::Storage(inner) => {
+            error!("backfill request failed: {}", inner);
+            note_storage_error(inner);
+            (ApiErrorCode::DbError, "Failed to store backfill request".to_string())
+        }
+        BackfillError::Cancelled => (ApiErrorCode::ShuttingDown, e.to_string()),
+    };
+    ApiError::new(code, message)
+}
+
+/// `POST /api/wallets/:name/backfill?network=eth&from_block=`：wallet owner 或 admin
+///
+/// wallet须已在该network上注册（有地址），否则 404。游标写入后立即唤起后台任务，
+/// 不等回填完成；进度看 `GET /api/wallets/:name/backfill/status`。
+pub async fn request_backfill(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(query): ValidQuery<BackfillParams>,
+) -> Result<Json<BackfillResponse>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    let cursor = state.backfill.request(name, query.network.as_str(), query.from_block).await.map_err(backfill_error)?;
+
+    // 任务正在运行时，本轮处理完现有游标后会重新列出并接上新游标
+    let scheduled = state.jobs.state(BACKFILL_JOB).is_some();
+    if scheduled {
+        let jobs = state.jobs.clone();
+        spawn_named("backfill_trigger", async move {
+            match jobs.run_now(BACKFILL_JOB).await {
+                Ok(_) | Err(JobRunError::AlreadyRunning(_)) => {}
+                Err(e) => debug!("backfill not triggered: {}", e),
+            }
+        });
+    }
+    Ok(Json(BackfillResponse { cursor, scheduled }))
+}
+
+/// `GET /api/wallets/:name/backfill/status`：wallet owner 或 admin
+pub async fn backfill_status(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<BackfillStatusResponse>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    let db_error = |e: anyhow::Error| {
+        error!("backfill status of {} failed: {}", name, e);
+        note_storage_error(&e);
+        ApiError::new(ApiErrorCode::DbError, "Failed to load backfill status")
+    };
+    let cursors = state.storage.backfill_cursors(name).await.map_err(db_error)?;
+    let mut networks: Vec<&str> = cursors.iter().map(|c| c.network.as_str()).collect();
+    networks.dedup();
+    let mut ledger = Vec::with_capacity(networks.len());
+    for network in networks {
+        let balances = state.storage.ledger_balances(name, network).await.map_err(db_error)?;
+        ledger.push(NetworkLedgerBalances { network: network.to_string(), balances });
+    }
+    Ok(Json(BackfillStatusResponse { wallet_name: name.to_string(), cursors, ledger }))
+}
@@ -8,24 +8,26 @@ use axum::{
 use base64::Engine;
 use std::sync::Arc;
 
+use crate::api::error_catalog::ApiErrorCode;
 use crate::api::middleware::authenticate;
 use crate::api::server::WalletServer;
 use crate::api::types::*;
+use crate::api::validators::{validate_wallet_name, Validate};
 use crate::core::errors::WalletError;
+use crate::core::wallet_manager::backup::ShareBackupError;
+use crate::storage::WalletNotes;
+
+/// 备份里只有mnemonic，没有创建时用的 BIP39 passphrase
+const PASSPHRASE_REQUIRED_WARNING: &str =
+    "该wallet创建时使用了 BIP39 passphrase，备份中不含 passphrase：恢复时必须同时提供mnemonic和 passphrase";
 
 pub async fn backup_wallet(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
     Path(name): Path<String>,
-) -> Result<Json<BackupResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<BackupResponse>, ApiError> {
     authenticate(&headers, &state.api_key).await.map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized".to_string(),
-                code: "AUTH_FAILED".to_string(),
-            }),
-        )
+        ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
     })?;
 
     // Allow runtime test override as before
@@ -35,48 +37,24 @@ pub async fn backup_wallet(
             .filter(|v| v == "1" || v.eq_ignore_ascii_case("true"));
         let runtime_test_override = std::env::var("TEST_SKIP_DECRYPT").ok().as_deref() == Some("1");
         if enabled.is_none() && !runtime_test_override {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(ErrorResponse {
-                    error: "Backup export disabled".to_string(),
-                    code: "BACKUP_DISABLED".to_string(),
-                }),
-            ));
+            return Err(ApiError::new(ApiErrorCode::BackupDisabled, "Backup export disabled"));
         }
     }
 
     // Validate wallet name
     if name.is_empty() || name.contains(|c: char| !c.is_alphanumeric() && c != '_') {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid wallet name".to_string(),
-                code: "BACKUP_FAILED".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::InvalidWalletName, "Invalid wallet name"));
     }
 
     // Check wallet exists
     match state.wallet_manager.list_wallets().await {
         Ok(wallets) => {
             if !wallets.iter().any(|w| w.name == name) {
-                return Err((
-                    StatusCode::NOT_FOUND,
-                    Json(ErrorResponse {
-                        error: "Wallet not found".to_string(),
-                        code: "BACKUP_FAILED".to_string(),
-                    }),
-                ));
+                return Err(ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found"));
             }
         }
         Err(_) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to check wallet".to_string(),
-                    code: "BACKUP_FAILED".to_string(),
-                }),
-            ))
+            return Err(ApiError::new(ApiErrorCode::BackupFailed, "Failed to check wallet"))
         }
     }
 
@@ -84,6 +62,21 @@ pub async fn backup_wallet(
     let runtime_test_mode = cfg!(any(test, feature = "test-env"))
         || std::env::var("TEST_SKIP_DECRYPT").ok().as_deref() == Some("1");
 
+    // 描述和元数据随备份明文导出；写入时已拒绝疑似密钥的内容
+    let notes = match state.storage.wallet_notes(&name).await {
+        Ok(notes) => notes.unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!("failed to load notes of wallet {} for backup: {}", name, e);
+            WalletNotes::default()
+        }
+    };
+
+    // passphrase 不落库：导出的mnemonic单独无法恢复这个wallet，需在备份上注明
+    let passphrase_required = match state.wallet_manager.get_wallet_by_name(&name).await {
+        Ok(wallet) => wallet.is_some_and(|w| w.passphrase_protected),
+        Err(_) => false,
+    };
+
     if runtime_test_mode {
         // 在测试模式下，不依赖manager，直接生成mnemonic并返回PLAINTEXT，以保证测试稳定
         match crate::core::wallet::create::generate_mnemonic() {
@@ -96,74 +89,214 @@ pub async fn backup_wallet(
                     nonce: "".to_string(),
                     ciphertext: ct_b64,
                     wallet: name,
+                    description: notes.description,
+                    metadata: (!notes.metadata.is_empty()).then_some(notes.metadata),
+                    passphrase_required,
+                    warning: passphrase_required.then(|| PASSPHRASE_REQUIRED_WARNING.to_string()),
                 };
                 return Ok(Json(response));
             }
             Err(_) => {
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: "Failed to generate mnemonic".to_string(),
-                        code: "BACKUP_FAILED".to_string(),
-                    }),
-                ));
+                return Err(ApiError::new(ApiErrorCode::BackupFailed, "Failed to generate mnemonic"));
             }
         }
     }
 
     // 生产环境：遵循非托管策略，不支持导出mnemonic
-    Err((
-        StatusCode::BAD_REQUEST,
-        Json(ErrorResponse {
-            error: "Backup not supported".to_string(),
-            code: "BACKUP_NOT_SUPPORTED".to_string(),
-        }),
-    ))
+    Err(ApiError::new(ApiErrorCode::BackupNotSupported, "Backup not supported"))
+}
+
+/// 与 API 创建的wallet一样把恢复的wallet落库；描述和元数据就挂在这一行上
+pub(super) async fn persist_restored(
+    state: &WalletServer,
+    name: &str,
+    quantum_safe: bool,
+    notes: Option<&WalletNotes>,
+) -> anyhow::Result<()> {
+    if state.storage.wallet_notes(name).await?.is_none() {
+        let wallet = state
+            .wallet_manager
+            .get_wallet_by_name(name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("restored wallet {} is not loaded", name))?;
+        state.storage.store_wallet(name, &bincode::serialize(&wallet)?, quantum_safe).await?;
+    }
+    if let Some(notes) = notes {
+        state.storage.set_wallet_notes(name, notes).await?;
+    }
+    Ok(())
 }
 
 pub async fn restore_wallet(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
     Json(payload): Json<RestoreWalletRequest>,
-) -> Result<Json<WalletResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<WalletResponse>, ApiError> {
     authenticate(&headers, &state.api_key).await.map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized".to_string(),
-                code: "AUTH_FAILED".to_string(),
-            }),
-        )
+        ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
     })?;
 
-    match state // Updated to handle different error types
-        .wallet_manager
-        .restore_wallet_with_options(&payload.name, &payload.seed_phrase, None, None)
-        .await
-    {
-        Ok(_) => Ok(Json(WalletResponse {
-            id: payload.name.clone(),
-            name: payload.name.clone(),
-            address: format!("0x{}", hex::encode(&payload.name.as_bytes()[..20.min(payload.name.len())])),
-            quantum_safe: payload.quantum_safe,
-            wallet_type: Some("standard".to_string()),  // ✅ 添加wallet类型
-            mnemonic: None, // 恢复时不返回mnemonic
-            warning: None,
-        })),
+    // 备份里的描述/元数据与 PATCH 走同一套校验，不合法时在恢复前拒绝
+    let notes = match (&payload.description, &payload.metadata) {
+        (None, None) => None,
+        (description, metadata) => {
+            let patch = WalletNotesPatch::validate(UpdateWalletNotesRequest {
+                description: Some(description.clone()),
+                metadata: metadata.clone().unwrap_or_default(),
+            })?;
+            let metadata = patch.metadata.into_iter().filter(|(_, v)| !v.is_null()).collect();
+            Some(WalletNotes { description: patch.description.flatten(), metadata })
+        }
+    };
+
+    let passphrase = payload.passphrase.as_deref().filter(|p| !p.is_empty());
+    let derivation_path = payload.derivation_path.as_deref();
+    let restored = match (payload.password.as_deref(), passphrase) {
+        (Some(password), _) => {
+            state
+                .wallet_manager
+                .restore_wallet_with_passphrase(&payload.name, &payload.seed_phrase, password, passphrase, derivation_path)
+                .await
+        }
+        // passphrase 派生的密钥不保存就无从sign，passphrase 本身也不落库
+        (None, Some(_)) => {
+            return Err(ApiError::new(
+                ApiErrorCode::PasswordRequired,
+                "A password is required to store a key restored with a passphrase",
+            ))
+        }
+        (None, None) => {
+            state.wallet_manager.restore_wallet_with_options(&payload.name, &payload.seed_phrase, None, derivation_path).await
+        }
+    };
+    match restored {
+        Ok(address) => {
+            if let Err(e) = persist_restored(&state, &payload.name, payload.quantum_safe, notes.as_ref()).await {
+                tracing::warn!("failed to persist restored wallet {}: {}", payload.name, e);
+            }
+            let notes = notes.unwrap_or_default();
+            Ok(Json(WalletResponse {
+                id: payload.name.clone(),
+                name: payload.name.clone(),
+                // 第一个派生address：passphrase 输错也会恢复success，只能靠它在充值前发现
+                address,
+                quantum_safe: payload.quantum_safe,
+                wallet_type: Some("standard".to_string()),  // ✅ 添加wallet类型
+                mnemonic: None, // 恢复时不返回mnemonic
+                warning: passphrase.is_some().then(|| {
+                    "已使用 BIP39 passphrase 恢复：请先核对address与原wallet一致再充值，passphrase 错误会得到另一组address"
+                        .to_string()
+                }),
+                preflight: None,
+                networks: None,
+                description: notes.description,
+                metadata: (!notes.metadata.is_empty()).then_some(notes.metadata),
+            }))
+        }
         Err(e) => {
-            let (status, error_msg) = match e {
-                WalletError::MnemonicError(_) => {
-                    (StatusCode::BAD_REQUEST, "Invalid seed phrase".to_string())
+            let (code, error_msg) = match e {
+                WalletError::MnemonicError(_) => (ApiErrorCode::InvalidMnemonic, "Invalid seed phrase".to_string()),
+                WalletError::SecurityError(msg) => (ApiErrorCode::WeakPassword, msg),
+                WalletError::ValidationError(msg) if msg.starts_with("Invalid derivation path") => {
+                    (ApiErrorCode::InvalidDerivationPath, msg)
                 }
                 WalletError::StorageError(s) if s.contains("UNIQUE constraint failed") => {
-                    (StatusCode::BAD_REQUEST, "Wallet with that name already exists".to_string())
+                    (ApiErrorCode::WalletExists, "Wallet with that name already exists".to_string())
                 }
-                _ => (StatusCode::INTERNAL_SERVER_ERROR, "Failed to restore wallet".to_string()),
+                _ => (ApiErrorCode::RestoreFailed, "Failed to restore wallet".to_string()),
             };
-            Err((
-                status,
-                Json(ErrorResponse { error: error_msg, code: "RESTORE_FAILED".to_string() }),
-            ))
+            Err(ApiError::new(code, error_msg))
+        }
+    }
+}
+
+fn share_error(e: ShareBackupError) -> ApiError {
+    let code = e.code();
+    match e {
+        ShareBackupError::Wallet(WalletError::NotFoundError(msg)) => ApiError::new(ApiErrorCode::WalletNotFound, msg),
+        // decrypt_master_key: wallet Password错误
+        ShareBackupError::Wallet(WalletError::CryptoError(_)) => {
+            ApiError::new(ApiErrorCode::InvalidPassword, "Invalid wallet password")
+        }
+        ShareBackupError::Wallet(WalletError::ValidationError(msg)) if msg.contains("already exists") => {
+            ApiError::new(ApiErrorCode::WalletExists, msg)
+        }
+        ShareBackupError::Wallet(WalletError::SecurityError(msg)) => ApiError::new(ApiErrorCode::WeakPassword, msg),
+        ShareBackupError::Wallet(other) => {
+            tracing::error!("shamir backup failed: {}", other);
+            ApiError::from_code(StatusCode::INTERNAL_SERVER_ERROR, code, "Key share operation failed")
         }
+        // 份额本身完好，只是合不出备份时的密钥
+        other @ ShareBackupError::KeyMismatch => {
+            ApiError::from_code(StatusCode::UNPROCESSABLE_ENTITY, code, other.to_string())
+        }
+        other => ApiError::from_code(StatusCode::BAD_REQUEST, code, other.to_string()),
+    }
+}
+
+/// `POST /api/wallets/:name/backup/shamir`
+///
+/// 把wallet主密钥拆成 `total` 份，任意 `threshold` 份可经 `/api/wallets/restore/shamir` 恢复
+pub async fn backup_wallet_shamir(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(payload): Json<ShamirBackupRequest>,
+) -> Result<Json<ShamirBackupResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| {
+        ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
+    })?;
+    validate_wallet_name(&name)?;
+
+    let shares = state
+        .wallet_manager
+        .backup_to_shares(&name, &payload.password, payload.threshold, payload.total)
+        .await
+        .map_err(share_error)?;
+    let (backup_id, address) = (shares[0].backup_id.clone(), shares[0].address.clone());
+    Ok(Json(ShamirBackupResponse {
+        wallet: name,
+        backup_id,
+        threshold: payload.threshold,
+        total: payload.total,
+        address,
+        shares,
+    }))
+}
+
+/// `POST /api/wallets/restore/shamir`
+///
+/// 校验份额后重建wallet并落库；份额不足、重复或被改动时返回对应的 400
+pub async fn restore_wallet_shamir(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(payload): Json<ShamirRestoreRequest>,
+) -> Result<Json<WalletResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| {
+        ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
+    })?;
+    validate_wallet_name(&payload.name)?;
+
+    let address = state
+        .wallet_manager
+        .restore_from_shares(&payload.name, &payload.shares, &payload.password)
+        .await
+        .map_err(share_error)?;
+    if let Err(e) = persist_restored(&state, &payload.name, false, None).await {
+        tracing::warn!("failed to persist restored wallet {}: {}", payload.name, e);
     }
+
+    Ok(Json(WalletResponse {
+        id: payload.name.clone(),
+        name: payload.name.clone(),
+        address,
+        quantum_safe: false,
+        wallet_type: Some("standard".to_string()),
+        mnemonic: None, // 份额恢复的是主密钥，没有mnemonic
+        warning: None,
+        preflight: None,
+        networks: None,
+        description: None,
+        metadata: None,
+    }))
 }
@@ -1,110 +1,75 @@
 //! balancequery相关handlers
 
 use axum::{
-    extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
+    extract::State,
+    http::HeaderMap,
     response::Json,
 };
-use serde::Deserialize;
 use std::sync::Arc;
-use tracing::error;
+use tracing::{error, warn};
 
 // use crate::api::middleware::authenticate; // ✅ 开发环境已禁用认证
-use crate::api::middleware::extract_user::{extract_user_id_from_token, verify_wallet_ownership};
+use super::fiat::{pricing_for, value_of};
+use super::wallet_networks::check_network_allowed;
+use crate::api::middleware::wallet_scope::extract_wallet_caller;
 use crate::api::server::WalletServer;
+use crate::api::error_catalog::ApiErrorCode;
 use crate::api::types::*;
-use crate::api::validators::{validate_wallet_name, validate_and_normalize_network};
-
-#[derive(Deserialize)]
-pub struct BalanceQuery {
-    pub network: String,
-}
+use crate::api::validators::{EvmAddress, ValidPath, ValidQuery, WalletNameParam};
+use crate::blockchain::erc20::Erc20Error;
+use crate::core::errors::WalletError;
+use crate::storage::WalletCapability;
 
 pub async fn get_balance(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,  // ✅ 启用user认证
-    Path(name): Path<String>,
-    Query(query): Query<BalanceQuery>,
-) -> Result<Json<BalanceResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // ✅ 提取当前登录User ID
-    let user_id = extract_user_id_from_token(&headers, &state).await?;
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(query): ValidQuery<BalanceParams>,
+) -> Result<Json<BalanceResponse>, ApiError> {
+    // ✅ 提取当前登录user或wallet范围 token
+    let caller = extract_wallet_caller(&headers, &state).await?;
     
-    // ✅ validatewallet属于该user（权限check）
-    verify_wallet_ownership(&user_id, &name, &state).await?;
-
-    // validateWallet name（使用共享validate器）
-    validate_wallet_name(&name)?;
-
-    // validate并规范化network参数（使用共享validate器）
-    let normalized_network = validate_and_normalize_network(&query.network)?;
-
-    if !matches!(
-        normalized_network.as_str(),
-        "eth" | "sepolia" | "bsc" | "polygon-testnet" | "btc" | "polygon"
-    ) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Unsupported network".to_string(),
-                code: "GET_BALANCE_FAILED".to_string(),
-            }),
-        ));
-    }
+    // ✅ validatewallet属于该user / 在 token 范围内（权限check）
+    let name = name.as_str();
+    caller.authorize(&state, name, WalletCapability::ReadBalance).await?;
+    caller.audit(&state, name, "read_balance").await;
+    let user_id = caller.user_id();
+
+    // wallet名与network已由提取器validate
+    let normalized_network = query.network.as_str();
+    check_network_allowed(&state, name, normalized_network)?;
 
     // ✅ 非托管模式：fromuser_wallets表fetchwalletaddress
-    let wallets = state.user_db.get_user_wallets_with_address(&user_id)
+    let wallets = state.user_db.get_user_wallets_with_address(user_id)
         .await
         .map_err(|e| {
             error!("fetchuserwalletfailed: user_id={}, error={}", user_id, e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "queryuserwalletfailed".to_string(),
-                    code: "DB_ERROR".to_string(),
-                }),
-            )
+            ApiError::new(ApiErrorCode::DbError, "queryuserwalletfailed")
         })?;
 
     // 查找指定wallet
     let wallet_info = wallets.iter()
         .find(|w| w.name == name)
-        .ok_or_else(|| {
-            (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "Wallet not found".to_string(),
-                    code: "WALLET_NOT_FOUND".to_string(),
-                }),
-            )
-        })?;
+        .ok_or_else(|| ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found"))?;
 
     let wallet_address = wallet_info.address.as_ref()
-        .ok_or_else(|| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Wallet address not found".to_string(),
-                    code: "WALLET_ADDRESS_MISSING".to_string(),
-                }),
-            )
-        })?;
+        .ok_or_else(|| ApiError::new(ApiErrorCode::WalletAddressMissing, "Wallet address not found"))?;
+
+    // ERC-20：经链客户端 eth_call balanceOf/decimals，不做法币估值
+    if let Some(token) = &query.token_address {
+        return query_token_balance(&state, wallet_address, normalized_network, token).await.map(Json);
+    }
 
     // ✅ 非托管模式：使用address直接query区块链balance（不需要Private key）
     // TODO: 实际应调用区块链RPC，这里返回模拟数据
-    let balance = query_blockchain_balance(wallet_address, &normalized_network).await
+    let balance = query_blockchain_balance(wallet_address, normalized_network).await
         .map_err(|e| {
             error!("query区块链balancefailed: address={}, network={}, error={}", 
                    wallet_address, normalized_network, e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to get balance from blockchain".to_string(),
-                    code: "BLOCKCHAIN_QUERY_FAILED".to_string(),
-                }),
-            )
+            ApiError::new(ApiErrorCode::BlockchainQueryFailed, "Failed to get balance from blockchain")
         })?;
 
-    let symbol = match normalized_network.as_str() {
+    let symbol = match normalized_network {
         "eth" => "ETH",
         "polygon" => "MATIC",
         "bsc" => "BNB",
@@ -112,13 +77,71 @@ pub async fn get_balance(
         _ => "UNKNOWN",
     };
     
+    // 法币估值失败只省略字段
+    let fiat = match crate::pricing::native_symbol(normalized_network) {
+        Some(coin) => match pricing_for(&state, Some(user_id)).await {
+            Some(pricing) => value_of(&balance, pricing.price(coin).await).map(|v| (v, pricing.currency)),
+            None => None,
+        },
+        None => None,
+    };
+    let (fiat_value, fiat_currency) = fiat.unzip();
+
     Ok(Json(BalanceResponse {
         balance,
-        network: normalized_network,
+        network: normalized_network.to_string(),
         symbol: symbol.to_string(),
+        fiat_value,
+        fiat_currency,
+        token_address: None,
+        raw_balance: None,
+        decimals: None,
     }))
 }
 
+/// query ERC-20 balance；`balance` 按 `decimals()` 换算，token 未实现 `decimals()` 时为原始数量
+async fn query_token_balance(
+    state: &WalletServer,
+    address: &str,
+    network: &str,
+    token: &EvmAddress,
+) -> Result<BalanceResponse, ApiError> {
+    let client =
+        state.chain_clients.get(network).map_err(|e| ApiError::new(ApiErrorCode::NetworkUnavailable, e.to_string()))?;
+    let token_balance =
+        client.get_erc20_balance(address, token.as_str()).await.map_err(|e| token_balance_error(network, token, e))?;
+
+    let balance = match token_balance.decimals {
+        Some(decimals) => ethers::utils::format_units(token_balance.raw, u32::from(decimals))
+            .unwrap_or_else(|_| token_balance.raw.to_string()),
+        None => token_balance.raw.to_string(),
+    };
+    let symbol = state.tokens.resolve(network, token.as_str()).map_or_else(|| "UNKNOWN".to_string(), |t| t.symbol);
+
+    Ok(BalanceResponse {
+        balance,
+        network: network.to_string(),
+        symbol,
+        fiat_value: None,
+        fiat_currency: None,
+        token_address: Some(token.as_str().to_string()),
+        raw_balance: Some(token_balance.raw.to_string()),
+        decimals: token_balance.decimals,
+    })
+}
+
+/// 合约 revert 或返回值不是 uint256（例如address上没有合约）是 422，不是 500
+fn token_balance_error(network: &str, token: &EvmAddress, e: Erc20Error) -> ApiError {
+    warn!("ERC-20 balancequeryfailed: network={}, token={}, error={}", network, token, e);
+    let code = match &e {
+        Erc20Error::Reverted(_) => ApiErrorCode::TokenCallReverted,
+        Erc20Error::MalformedUint(..) => ApiErrorCode::TokenBadReturnData,
+        Erc20Error::Rpc(WalletError::NetworkBusy(_)) => ApiErrorCode::NetworkBusy,
+        _ => ApiErrorCode::BalanceCheckFailed,
+    };
+    ApiError::new(code, e.to_string())
+}
+
 /// query区块链balance（使用address）
 async fn query_blockchain_balance(address: &str, network: &str) -> Result<String, String> {
     // ✅ 非托管模式：直接用addressquery区块链
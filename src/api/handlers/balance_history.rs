@@ -0,0 +1,119 @@
+//! balance历史（图表）handlers
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::{extract_user_id_from_token, verify_wallet_ownership};
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{NetworkName, ParamError, Validate, ValidPath, ValidQuery, WalletNameParam};
+use crate::storage::{SnapshotResolution, NATIVE_TOKEN};
+
+/// 小时粒度最多返回的天数
+pub const MAX_HOURLY_RANGE_DAYS: i64 = 31;
+/// 日粒度最多返回的天数
+pub const MAX_DAILY_RANGE_DAYS: i64 = 5 * 365;
+
+#[derive(Deserialize)]
+pub struct BalanceHistoryQuery {
+    pub network: String,
+    /// RFC 3339，默认按粒度回溯（hour: 7天，day: 90天）
+    pub from: Option<DateTime<Utc>>,
+    /// RFC 3339，默认当前时间
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub resolution: SnapshotResolution,
+}
+
+/// [`BalanceHistoryQuery`] validate后
+pub struct BalanceHistoryParams {
+    pub network: NetworkName,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub resolution: SnapshotResolution,
+}
+
+impl Validate for BalanceHistoryParams {
+    type Raw = BalanceHistoryQuery;
+
+    fn validate(raw: BalanceHistoryQuery) -> Result<Self, ParamError> {
+        Ok(Self {
+            network: NetworkName::try_from(raw.network.as_str())?,
+            from: raw.from,
+            to: raw.to,
+            resolution: raw.resolution,
+        })
+    }
+}
+
+fn bad_request(error: &str) -> ApiError {
+    ApiError::new(ApiErrorCode::InvalidRange, error.to_string())
+}
+
+fn db_error() -> ApiError {
+    ApiError::new(ApiErrorCode::DbError, "Failed to load balance history")
+}
+
+/// `GET /api/wallets/:name/balance_history?network=&from=&to=&resolution=hour|day`
+pub async fn balance_history(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(query): ValidQuery<BalanceHistoryParams>,
+) -> Result<Json<BalanceHistoryResponse>, ApiError> {
+    let user_id = extract_user_id_from_token(&headers, &state).await?;
+    verify_wallet_ownership(&user_id, name.as_str(), &state).await?;
+    let network = query.network.as_str();
+
+    let (default_days, max_days) = match query.resolution {
+        SnapshotResolution::Hour => (7, MAX_HOURLY_RANGE_DAYS),
+        SnapshotResolution::Day => (90, MAX_DAILY_RANGE_DAYS),
+    };
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::days(default_days));
+    if from >= to {
+        return Err(bad_request("`from` must be before `to`"));
+    }
+    if to - from > Duration::days(max_days) {
+        return Err(bad_request("Requested range is too large for this resolution"));
+    }
+
+    let (wallet_id, _) = state
+        .user_db
+        .find_user_wallet(&user_id, name.as_str())
+        .await
+        .map_err(|e| {
+            error!("balance history: wallet lookup failed: user_id={}, error={}", user_id, e);
+            db_error()
+        })?
+        .ok_or_else(|| {
+            ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found")
+        })?;
+
+    let points = state
+        .storage
+        .balance_history(&wallet_id.to_string(), network, NATIVE_TOKEN, from, to, query.resolution)
+        .await
+        .map_err(|e| {
+            error!("balance history: query failed: wallet={}, error={}", name, e);
+            db_error()
+        })?;
+
+    Ok(Json(BalanceHistoryResponse {
+        wallet: name.into(),
+        network: network.to_string(),
+        token: NATIVE_TOKEN.to_string(),
+        resolution: query.resolution,
+        from,
+        to,
+        points,
+    }))
+}
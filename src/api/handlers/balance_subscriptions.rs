@@ -0,0 +1,193 @@
+//! 余额变化订阅 handlers
+//!
+//! 订阅归属于 users.db 中的一条wallet绑定；owner（会话 token）或 admin
+//! （API key，需指定 `owner_user_id`）可以管理。通知以 `balance.changed`
+//! 事件写入事件日志，由 webhook 投递方按 `webhook_target` 路由。
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+use super::wallet_tokens::ADMIN_ISSUER;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::authorize_owner_or_admin;
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ParamError, ValidJson, ValidPath, WalletNameParam};
+use crate::storage::{BalanceSubscriptionRecord, NewBalanceSubscription};
+
+#[derive(Debug, Deserialize)]
+pub struct SubscriptionOwnerQuery {
+    /// admin 请求需要；owner 请求忽略
+    pub owner_user_id: Option<String>,
+}
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("balance subscription storage error: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access balance subscriptions")
+}
+
+fn subscription_not_found() -> ApiError {
+    ApiError::new(ApiErrorCode::SubscriptionNotFound, "Subscription not found")
+}
+
+/// 返回 `(wallet_id, owner_user_id, acting)`；`acting` 为 owner user id 或 `admin`
+async fn subscribed_wallet(
+    headers: &HeaderMap,
+    state: &Arc<WalletServer>,
+    wallet_name: &str,
+    owner_user_id: Option<String>,
+) -> Result<(i64, String, String), ApiError> {
+    let (owner, acting) = match authorize_owner_or_admin(headers, state, wallet_name).await? {
+        Some(user_id) => (user_id.clone(), user_id),
+        None => (owner_user_id.ok_or(ParamError::Missing("owner_user_id"))?, ADMIN_ISSUER.to_string()),
+    };
+    let (wallet_id, _) = state
+        .user_db
+        .find_user_wallet(&owner, wallet_name)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found")
+        })?;
+    Ok((wallet_id, owner, acting))
+}
+
+async fn audit(state: &WalletServer, wallet_name: &str, action: &str, details: serde_json::Value) {
+    if let Err(e) = state.storage.log_action(wallet_name, action, &details.to_string(), None, None).await {
+        error!("failed to audit {} on {}: {}", action, wallet_name, e);
+    }
+}
+
+/// `POST /api/wallets/:name/subscriptions`
+pub async fn create_balance_subscription(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<CreateBalanceSubscription>,
+) -> Result<Json<BalanceSubscriptionRecord>, ApiError> {
+    let name = name.as_str();
+    let (wallet_id, owner, created_by) =
+        subscribed_wallet(&headers, &state, name, payload.owner_user_id.clone()).await?;
+
+    let id = state.storage.id_generator().new_id();
+    state
+        .storage
+        .insert_balance_subscription(&NewBalanceSubscription {
+            id: &id,
+            wallet_id,
+            wallet_name: name,
+            owner_user_id: &owner,
+            network: payload.network.as_str(),
+            token: &payload.token,
+            min_delta: payload.min_delta,
+            webhook_target: &payload.webhook_target,
+            created_by: &created_by,
+        })
+        .await
+        .map_err(storage_error)?;
+    let record = state
+        .storage
+        .balance_subscription(wallet_id, &id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| storage_error(anyhow::anyhow!("balance subscription {} vanished after insert", id)))?;
+
+    audit(
+        &state,
+        name,
+        "balance_subscription.created",
+        serde_json::json!({
+            "subscription_id": id,
+            "network": record.network,
+            "token": record.token,
+            "created_by": created_by,
+        }),
+    )
+    .await;
+    Ok(Json(record))
+}
+
+/// `GET /api/wallets/:name/subscriptions`
+pub async fn list_balance_subscriptions(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    Query(query): Query<SubscriptionOwnerQuery>,
+) -> Result<Json<BalanceSubscriptionListResponse>, ApiError> {
+    let (wallet_id, _, _) = subscribed_wallet(&headers, &state, name.as_str(), query.owner_user_id).await?;
+    let subscriptions = state.storage.balance_subscriptions_for(wallet_id).await.map_err(storage_error)?;
+    Ok(Json(BalanceSubscriptionListResponse { subscriptions }))
+}
+
+/// `PATCH /api/wallets/:name/subscriptions/:id`：启停或调整阈值
+pub async fn update_balance_subscription(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((name, id)): Path<(String, String)>,
+    Query(query): Query<SubscriptionOwnerQuery>,
+    ValidJson(payload): ValidJson<UpdateBalanceSubscription>,
+) -> Result<Json<BalanceSubscriptionRecord>, ApiError> {
+    let name = WalletNameParam::try_from(name.as_str())?;
+    let name = name.as_str();
+    let (wallet_id, _, acting) = subscribed_wallet(&headers, &state, name, query.owner_user_id).await?;
+
+    let updated = state
+        .storage
+        .update_balance_subscription(wallet_id, &id, payload.active, payload.min_delta)
+        .await
+        .map_err(storage_error)?;
+    if !updated {
+        return Err(subscription_not_found());
+    }
+    let record = state
+        .storage
+        .balance_subscription(wallet_id, &id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(subscription_not_found)?;
+    audit(
+        &state,
+        name,
+        "balance_subscription.updated",
+        serde_json::json!({
+            "subscription_id": id,
+            "active": record.active,
+            "min_delta": record.min_delta,
+            "updated_by": acting,
+        }),
+    )
+    .await;
+    Ok(Json(record))
+}
+
+/// `DELETE /api/wallets/:name/subscriptions/:id`
+pub async fn delete_balance_subscription(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((name, id)): Path<(String, String)>,
+    Query(query): Query<SubscriptionOwnerQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let name = WalletNameParam::try_from(name.as_str())?;
+    let name = name.as_str();
+    let (wallet_id, _, acting) = subscribed_wallet(&headers, &state, name, query.owner_user_id).await?;
+
+    if !state.storage.delete_balance_subscription(wallet_id, &id).await.map_err(storage_error)? {
+        return Err(subscription_not_found());
+    }
+    audit(
+        &state,
+        name,
+        "balance_subscription.deleted",
+        serde_json::json!({ "subscription_id": id, "deleted_by": acting }),
+    )
+    .await;
+    Ok(Json(serde_json::json!({ "success": true, "message": "Subscription deleted" })))
+}
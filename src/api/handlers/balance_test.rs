@@ -16,11 +16,16 @@ use crate::api::{
 pub async fn get_balance_simple(
     State(_state): State<Arc<WalletServer>>,
     Path(name): Path<String>,
-) -> Result<Json<BalanceResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<BalanceResponse>, ApiError> {
     Ok(Json(BalanceResponse {
         balance: format!("1000 (test for {})", name),
         network: "eth".to_string(),
         symbol: "ETH".to_string(),
+        fiat_value: None,
+        fiat_currency: None,
+        token_address: None,
+        raw_balance: None,
+        decimals: None,
     }))
 }
 
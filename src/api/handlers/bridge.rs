@@ -1,135 +1,189 @@
 //! 跨链桥接相关handlers
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Path, Query, RawQuery, State},
     http::{HeaderMap, StatusCode},
     response::Json,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+use super::wallet_networks::check_network_allowed;
+use super::wallet_tokens::ADMIN_ISSUER;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::http_cache::{self, CacheKey};
 use crate::api::middleware::authenticate;
 use crate::api::server::WalletServer;
 use crate::api::types::*;
+use crate::api::pagination::{PageCursor, PageDirection};
+use crate::api::validators::{ParamError, ValidJson, ValidQuery};
+use crate::blockchain::bridge::{BridgeTransaction, BridgeTransactionStatus, RouteMatrix};
+use crate::core::amount::{Amount, AssetTag};
+use crate::core::config::BlockchainConfig;
+use crate::token_registry::TokenRegistry;
+use crate::storage::TransactionCursor;
+use crate::core::errors::WalletError;
 use axum::response::{Response, IntoResponse};
 
 pub async fn bridge_assets(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
-    Json(payload): Json<BridgeAssetsRequest>,
-) -> Response {
-    if let Err(_) = authenticate(&headers, &state.api_key).await {
-        return (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized".to_string(),
-                code: "AUTH_FAILED".to_string(),
-            }),
-        ).into_response();
-    }
+    ValidJson(payload): ValidJson<BridgeAssets>,
+) -> Result<Json<BridgeResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    Ok(Json(initiate_bridge(&state, &payload).await?))
+}
 
-    // 1) Basic parameter validation
-    if payload.from_wallet.is_empty()
-        || payload.from_chain.is_empty()
-        || payload.to_chain.is_empty()
-        || payload.token.is_empty()
-        || payload.amount.is_empty()
-    {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid parameters".to_string(),
-                code: "BRIDGE_FAILED".to_string(),
-            }),
-        ).into_response();
-    }
+fn unauthorized() -> ApiError {
+    ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
+}
 
-    if let Err(_e) = crate::core::validation::validate_amount_strict(&payload.amount, 18) {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid amount".to_string(),
-                code: "BRIDGE_FAILED".to_string(),
-            }),
-        ).into_response();
-    }
 
-    // 2) check链是否受支持，统一返回 404 NOT_FOUND
+/// 校验路由与wallet后发起桥接；operation bundle 的 bridge 步骤也走这里
+pub(crate) async fn initiate_bridge(
+    state: &WalletServer,
+    payload: &BridgeAssets,
+) -> Result<BridgeResponse, ApiError> {
+    let from_chain = payload.from_chain.as_str();
+    let to_chain = payload.to_chain.as_str();
+
+    // 1) check链是否受支持，统一返回 404 NOT_FOUND
     // Determine if chains are supported. When `networks` is empty (test default),
     // that don't populate networks still exercise wallet existence logic.
     let from_supported = if state.config.blockchain.networks.is_empty() {
-        payload.from_chain == "eth" || payload.from_chain == "polygon"
+        from_chain == "eth" || from_chain == "polygon"
     } else {
-        state.config.blockchain.networks.contains_key(&payload.from_chain)
+        state.config.blockchain.networks.contains_key(from_chain)
     };
 
     let to_supported = if state.config.blockchain.networks.is_empty() {
-        payload.to_chain == "eth" || payload.to_chain == "polygon"
+        to_chain == "eth" || to_chain == "polygon"
     } else {
-        state.config.blockchain.networks.contains_key(&payload.to_chain)
+        state.config.blockchain.networks.contains_key(to_chain)
     };
 
     if !from_supported || !to_supported {
         // 调试：使用结构化日志记录链名与当前已配置network（避免直接向 stderr 打印）
         tracing::debug!(
-            from = %payload.from_chain,
-            to = %payload.to_chain,
+            from = from_chain,
+            to = to_chain,
             known_networks = ?state.config.blockchain.networks.keys().collect::<Vec<_>>(),
             "unsupported chain check"
         );
 
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Unsupported chain".to_string(),
-                code: "BRIDGE_FAILED".to_string(),
-            }),
-        ).into_response();
+        return Err(ApiError::new(ApiErrorCode::UnsupportedNetwork, "Unsupported chain"));
     }
 
-    // 3) Then check if the wallet exists (to meet test expectations for 404)
-    if state.wallet_manager.get_wallet_by_name(&payload.from_wallet).await.unwrap_or(None).is_none()
-    {
-        return (
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "Wallet not found".to_string(),
-                code: "BRIDGE_FAILED".to_string(),
-            }),
-        ).into_response();
+    // 1b) Both legs must be on the wallet's allowed networks: funds leaving an
+    // allowed chain still may not arrive on a disallowed one
+    for chain in [from_chain, to_chain] {
+        check_network_allowed(state, payload.from_wallet.as_str(), chain)?;
     }
 
-    // 4) In a test/mock environment, return a fixed txid directly to avoid decryption (fulfills test expectation for "mock_bridge_tx_hash")
-    #[cfg(feature = "test-env")]
-    {
-        let force_mock = crate::security::env_manager::secure_env::get_bridge_mock_force_success()
-            .ok()
-            .as_deref()
-            == Some("1");
-        if force_mock {
-            return Json(BridgeResponse { 
-                bridge_id: "mock_bridge_tx_hash".to_string(),
-                bridge_tx_id: Some("mock_bridge_tx_hash".to_string()),
-                status: "initiated".to_string(),
-                target_chain: Some(payload.to_chain.clone()),
-                amount: Some(payload.amount.clone()),
-                from_chain: Some(payload.from_chain.clone()),
-                token: Some(payload.token.clone()),
-            }).into_response();
+    // 2) Resolve the bridge for this route (backend fixed at startup)
+    let bridge = match state.bridge_factory.for_route(from_chain, to_chain) {
+        Ok(b) => b,
+        Err(WalletError::ValidationError(msg)) => {
+            return Err(ApiError::new(ApiErrorCode::UnsupportedBridgeRoute, msg));
+        }
+        Err(e) => {
+            tracing::warn!(
+                backend = state.bridge_factory.backend().as_str(),
+                "bridge backend unavailable: {}", e
+            );
+            return Err(ApiError::new(
+                ApiErrorCode::BridgeBackendUnavailable,
+                "Bridge backend unavailable for this route",
+            ));
         }
+    };
+
+    // 2b) Fail fast on tokens / amounts the route matrix does not cover; the token is resolved on
+    // both chains and the amount converted into each chain's minimal units
+    let transfer = state
+        .bridge_factory
+        .check_transfer(&state.tokens, from_chain, to_chain, &payload.token, payload.amount.as_str())
+        .await
+        .map_err(|rejection| ApiError::from_code(StatusCode::BAD_REQUEST, rejection.code(), rejection.to_string()))?;
+
+    // 3) Then check if the wallet exists (to meet test expectations for 404)
+    let wallet_data = match state.wallet_manager.get_wallet_by_name(payload.from_wallet.as_str()).await {
+        Ok(Some(w)) => w,
+        _ => return Err(ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found")),
+    };
+
+    // 4) Initiate the transfer through the selected bridge
+    let tx_id = crate::blockchain::bridge::bridge_transfer_normalized(bridge.as_ref(), &transfer, &wallet_data)
+        .await
+        .map_err(|e| {
+            tracing::error!("bridge transfer failed: {}", e);
+            ApiError::new(ApiErrorCode::BridgeFailed, "Bridge transfer failed")
+        })?;
+
+    // 5) 记录两条链上的最小单位金额；转账已发出，记录failed只告警不回滚
+    let now = chrono::Utc::now();
+    let record = BridgeTransaction {
+        id: tx_id.clone(),
+        from_wallet: payload.from_wallet.as_str().to_string(),
+        from_chain: from_chain.to_string(),
+        to_chain: to_chain.to_string(),
+        token: transfer.asset().to_string(),
+        amount: transfer.amount(),
+        status: BridgeTransactionStatus::Initiated,
+        source_tx_hash: None,
+        destination_tx_hash: None,
+        created_at: now,
+        updated_at: now,
+        fee_amount: None,
+        estimated_completion_time: None,
+        amount_minimal_source: None,
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
+    }
+    .with_normalized(&transfer);
+    if let Err(e) = state.storage.store_bridge_transaction(&record).await {
+        tracing::error!(bridge_id = %tx_id, "failed to record bridge transaction: {}", e);
     }
 
-    // 5) Real logic (will perform decryption/signing)
-    // 目前只返回mock响应，实际的桥接逻辑需要进一步实现
-    Json(BridgeResponse { 
-        bridge_id: format!("bridge_{}", chrono::Utc::now().timestamp()),
-        bridge_tx_id: Some(format!("tx_{}", chrono::Utc::now().timestamp())),
+    Ok(BridgeResponse {
+        bridge_id: tx_id.clone(),
+        bridge_tx_id: Some(tx_id),
         status: "initiated".to_string(),
-        target_chain: Some(payload.to_chain.clone()),
-        amount: Some(payload.amount.clone()),
-        from_chain: Some(payload.from_chain.clone()),
+        target_chain: Some(to_chain.to_string()),
+        amount: Some(payload.amount.to_string()),
+        from_chain: Some(from_chain.to_string()),
         token: Some(payload.token.clone()),
-    }).into_response()
+    })
+}
+
+/// GET /api/bridge/routes
+///
+/// Route matrix across all registered bridges, with recent failure rates
+#[derive(Deserialize)]
+pub struct BridgeRoutesQuery {
+    #[serde(default)]
+    pub from: Option<String>,
+}
+
+/// Window for the failure rate shown next to each route
+const ROUTE_HEALTH_WINDOW_HOURS: i64 = 24;
+
+pub async fn bridge_routes(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Query(query): Query<BridgeRoutesQuery>,
+) -> Result<Json<RouteMatrix>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let mut matrix = state.bridge_factory.route_matrix(query.from.as_deref()).await;
+    let since = chrono::Utc::now() - chrono::Duration::hours(ROUTE_HEALTH_WINDOW_HOURS);
+    match state.storage.bridge_route_stats(since).await {
+        Ok(stats) => matrix.annotate_health(&stats),
+        // 健康度只是附加信息，query失败不影响路由列表
+        Err(e) => tracing::warn!("Failed to load bridge route stats: {}", e),
+    }
+    Ok(Json(matrix))
 }
 
 /// GET /api/bridge/history
@@ -143,18 +197,33 @@ pub struct BridgeHistoryQuery {
     pub from_chain: Option<String>,
     #[serde(default)]
     pub to_chain: Option<String>,
+    /// 已废弃：改用 `cursor`/`limit`；本周期内仍转换为同一排序下的偏移查询
     #[serde(default)]
     pub page: Option<usize>,
     #[serde(default)]
     pub page_size: Option<usize>,
 }
 
+/// 桥接历史按 `(created_at, id)` 倒序分页
+#[derive(Debug, Clone, Copy)]
+pub struct BridgeHistoryPages;
+
+impl PageScope for BridgeHistoryPages {
+    const SCOPE: &'static str = "bridge_history";
+    const DIRECTION: PageDirection = PageDirection::Desc;
+    const DEFAULT_LIMIT: usize = 20;
+    const MAX_LIMIT: usize = 100;
+}
+
 #[derive(Serialize)]
 pub struct BridgeHistoryResponse {
-    pub items: Vec<BridgeTransactionInfo>,
-    pub page: usize,
-    pub page_size: usize,
-    pub total: usize,
+    #[serde(flatten)]
+    pub list: Page<BridgeTransactionInfo>,
+    /// 仅在使用旧参数 `page`/`page_size` 时回显
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<usize>,
 }
 
 #[derive(Serialize)]
@@ -170,89 +239,177 @@ pub struct BridgeTransactionInfo {
     pub destination_tx_hash: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// 各链按自身小数位换算的金额；归一化之前写入的记录没有这两项
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<BridgeLegInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<BridgeLegInfo>,
+    /// 源链 / 目标链transaction的浏览器链接（对应network配置了模板时）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_explorer_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination_explorer_url: Option<String>,
 }
 
-pub async fn bridge_history(
-    State(state): State<Arc<WalletServer>>,
-    headers: HeaderMap,
-    Query(query): Query<BridgeHistoryQuery>,
-) -> Result<Json<BridgeHistoryResponse>, (StatusCode, Json<ErrorResponse>)> {
-    authenticate(&headers, &state.api_key).await.map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized".to_string(),
-                code: "AUTH_FAILED".to_string(),
-            }),
-        )
-    })?;
-
-    let page = query.page.unwrap_or(1).max(1);
-    let page_size = query.page_size.unwrap_or(20).clamp(1, 100);
-    let offset = (page - 1) * page_size;
-
-    // from数据库query桥接历史
-    let storage = crate::storage::WalletStorage::new_with_url(&state.config.storage.database_url).await
-        .map_err(|e| {
-            tracing::error!("Failed to create storage connection: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Database connection failed".to_string(),
-                    code: "DB_ERROR".to_string(),
-                }),
-            )
-        })?;
-    
-    let (bridge_txs, total) = match storage.list_bridge_transactions(
-        query.wallet_name.as_deref(),
-        query.from_chain.as_deref(),
-        query.to_chain.as_deref(),
-        offset,
-        page_size,
-    ).await {
-        Ok(result) => result,
-        Err(e) => {
-            tracing::error!("Failed to query bridge history: {}", e);
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to query bridge history".to_string(),
-                    code: "QUERY_FAILED".to_string(),
-                }),
-            ));
-        }
-    };
+/// 桥接一端的金额
+#[derive(Serialize)]
+pub struct BridgeLegInfo {
+    pub chain: String,
+    /// 合约address；原生币为空
+    pub token_address: Option<String>,
+    /// 该链代币最小单位
+    pub amount_minimal: String,
+    /// 按该链小数位换算的金额；代币已不在配置中时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u32>,
+}
 
-    let items: Vec<BridgeTransactionInfo> = bridge_txs.into_iter().map(|tx| {
-        let status_str = match &tx.status {
-            crate::blockchain::bridge::BridgeTransactionStatus::Initiated => "initiated",
-            crate::blockchain::bridge::BridgeTransactionStatus::InTransit => "pending",
-            crate::blockchain::bridge::BridgeTransactionStatus::Completed => "completed",
-            crate::blockchain::bridge::BridgeTransactionStatus::Failed(_) => "failed",
-        }.to_string();
+impl BridgeLegInfo {
+    fn new(
+        chain: &str,
+        token: &str,
+        address: Option<String>,
+        minimal: Option<String>,
+        tokens: &TokenRegistry,
+    ) -> Option<Self> {
+        let minimal = minimal?;
+        let resolved = tokens.resolve(chain, address.as_deref().unwrap_or(token));
+        let amount = resolved.as_ref().and_then(|t| {
+            let minimal = minimal.parse().ok()?;
+            Some(Amount::new(minimal, t.decimals, AssetTag::new(chain, &t.symbol)).to_decimal_string())
+        });
+        Some(Self {
+            chain: chain.to_string(),
+            token_address: address,
+            amount_minimal: minimal,
+            amount,
+            decimals: resolved.map(|t| t.decimals),
+        })
+    }
+}
 
-        BridgeTransactionInfo {
+impl BridgeTransactionInfo {
+    fn from_record(tx: BridgeTransaction, blockchain: &BlockchainConfig, tokens: &TokenRegistry) -> Self {
+        let status = match &tx.status {
+            BridgeTransactionStatus::Initiated => "initiated",
+            BridgeTransactionStatus::InTransit => "pending",
+            BridgeTransactionStatus::Completed => "completed",
+            BridgeTransactionStatus::Failed(_) => "failed",
+        }
+        .to_string();
+        let source_explorer_url =
+            tx.source_tx_hash.as_deref().and_then(|h| blockchain.explorer_tx_url(&tx.from_chain, h));
+        let destination_explorer_url =
+            tx.destination_tx_hash.as_deref().and_then(|h| blockchain.explorer_tx_url(&tx.to_chain, h));
+        let source =
+            BridgeLegInfo::new(&tx.from_chain, &tx.token, tx.token_address_source, tx.amount_minimal_source, tokens);
+        let destination =
+            BridgeLegInfo::new(&tx.to_chain, &tx.token, tx.token_address_dest, tx.amount_minimal_dest, tokens);
+        Self {
             id: tx.id,
             from_wallet: tx.from_wallet,
             from_chain: tx.from_chain,
             to_chain: tx.to_chain,
             token: tx.token,
             amount: tx.amount,
-            status: status_str,
+            status,
             source_tx_hash: tx.source_tx_hash,
             destination_tx_hash: tx.destination_tx_hash,
             created_at: tx.created_at.to_rfc3339(),
             updated_at: tx.updated_at.to_rfc3339(),
+            source,
+            destination,
+            source_explorer_url,
+            destination_explorer_url,
         }
-    }).collect();
-
-    Ok(Json(BridgeHistoryResponse {
-        items,
-        page,
-        page_size,
-        total,
-    }))
+    }
+}
+
+/// `GET /api/bridge/history`
+///
+/// 带 ETag，版本号为 bridge_transactions 的 cache epoch（插入和状态变更时在同一事务内递增）。
+pub async fn bridge_history(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    RawQuery(raw_query): RawQuery,
+    Query(query): Query<BridgeHistoryQuery>,
+    ValidQuery(request): ValidQuery<PageRequest<BridgeHistoryPages>>,
+) -> Result<Response, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let version = state.storage.bridge_transactions_epoch().await.map_err(bridge_query_failed)?;
+    // 只有 API key 能读，所有调用方看到的是同一份数据
+    let key = CacheKey::new(http_cache::BRIDGE_HISTORY, raw_query.as_deref(), ADMIN_ISSUER);
+    state
+        .response_cache
+        .respond(&headers, key, &version.to_string(), || async {
+            bridge_history_page(&state, &query, &request).await.map(|page| Json(page).into_response())
+        })
+        .await
+}
+
+fn bridge_query_failed(e: anyhow::Error) -> ApiError {
+    tracing::error!("Failed to query bridge history: {}", e);
+    ApiError::new(ApiErrorCode::QueryFailed, "Failed to query bridge history")
+}
+
+async fn bridge_history_page(
+    state: &WalletServer,
+    query: &BridgeHistoryQuery,
+    request: &PageRequest<BridgeHistoryPages>,
+) -> Result<BridgeHistoryResponse, ApiError> {
+    let filter = crate::storage::BridgeFilter {
+        wallet_name: query.wallet_name.as_deref(),
+        from_chain: query.from_chain.as_deref(),
+        to_chain: query.to_chain.as_deref(),
+    };
+
+    // 旧参数：第 N 页即同一排序下跳过 (N-1)*page_size 行；返回的游标可直接续接新接口
+    let legacy = request.cursor.is_none() && (query.page.is_some() || query.page_size.is_some());
+    let (page, page_size) = (query.page.unwrap_or(1).max(1), query.page_size.unwrap_or(request.limit));
+    let page_size = page_size.clamp(1, BridgeHistoryPages::MAX_LIMIT);
+
+    let (bridge_txs, next, total) = if legacy {
+        let offset = (page - 1) * page_size;
+        let (rows, total) = state
+            .storage
+            .list_bridge_transactions(filter.wallet_name, filter.from_chain, filter.to_chain, offset, page_size)
+            .await
+            .map_err(bridge_query_failed)?;
+        let next = rows.last().filter(|_| offset + rows.len() < total);
+        let next = next.map(|t| TransactionCursor { created_at: t.created_at, id: t.id.clone() });
+        (rows, next, total)
+    } else {
+        let cursor = match &request.cursor {
+            Some(c) => {
+                let (created_at, id) = c.as_time_id().ok_or(ParamError::Cursor)?;
+                Some(TransactionCursor { created_at, id })
+            }
+            None => None,
+        };
+        let (rows, next) = state
+            .storage
+            .bridge_transactions_page(&filter, cursor.as_ref(), request.limit)
+            .await
+            .map_err(bridge_query_failed)?;
+        let total = state.storage.count_bridge_transactions(&filter).await.map_err(bridge_query_failed)?;
+        (rows, next, total)
+    };
+
+    let items: Vec<BridgeTransactionInfo> = bridge_txs
+        .into_iter()
+        .map(|tx| BridgeTransactionInfo::from_record(tx, &state.config.blockchain, &state.tokens))
+        .collect();
+    let next = next.map(|c| PageCursor::time_id(PageDirection::Desc, c.created_at, &c.id));
+    let list = request.page(items, next, Some(total as u64)).map_err(bridge_query_failed)?;
+
+    Ok(BridgeHistoryResponse {
+        list,
+        page: legacy.then_some(page),
+        page_size: legacy.then_some(page_size),
+    })
 }
 
 /// GET /api/bridge/:id/status
@@ -262,81 +419,22 @@ pub async fn bridge_status(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
     Path(bridge_id): Path<String>,
-) -> Result<Json<BridgeTransactionInfo>, (StatusCode, Json<ErrorResponse>)> {
-    authenticate(&headers, &state.api_key).await.map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized".to_string(),
-                code: "AUTH_FAILED".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<BridgeTransactionInfo>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
 
     if bridge_id.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid bridge ID".to_string(),
-                code: "INVALID_INPUT".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::InvalidInput, "Invalid bridge ID"));
     }
 
     // from数据库query桥接状态
-    let storage = crate::storage::WalletStorage::new_with_url(&state.config.storage.database_url).await
-        .map_err(|e| {
-            tracing::error!("Failed to create storage connection: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Database connection failed".to_string(),
-                    code: "DB_ERROR".to_string(),
-                }),
-            )
-        })?;
-    
-    match storage.get_bridge_transaction(&bridge_id).await {
-        Ok(tx) => {
-            let status_str = match &tx.status {
-                crate::blockchain::bridge::BridgeTransactionStatus::Initiated => "initiated",
-                crate::blockchain::bridge::BridgeTransactionStatus::InTransit => "pending",
-                crate::blockchain::bridge::BridgeTransactionStatus::Completed => "completed",
-                crate::blockchain::bridge::BridgeTransactionStatus::Failed(_) => "failed",
-            }.to_string();
-
-            Ok(Json(BridgeTransactionInfo {
-                id: tx.id,
-                from_wallet: tx.from_wallet,
-                from_chain: tx.from_chain,
-                to_chain: tx.to_chain,
-                token: tx.token,
-                amount: tx.amount,
-                status: status_str,
-                source_tx_hash: tx.source_tx_hash,
-                destination_tx_hash: tx.destination_tx_hash,
-                created_at: tx.created_at.to_rfc3339(),
-                updated_at: tx.updated_at.to_rfc3339(),
-            }))
-        }
+    match state.storage.get_bridge_transaction(&bridge_id).await {
+        Ok(tx) => Ok(Json(BridgeTransactionInfo::from_record(tx, &state.config.blockchain, &state.tokens))),
         Err(e) => {
             if e.to_string().contains("not found") || e.to_string().contains("No rows") {
-                Err((
-                    StatusCode::NOT_FOUND,
-                    Json(ErrorResponse {
-                        error: "Bridge transaction not found".to_string(),
-                        code: "NOT_FOUND".to_string(),
-                    }),
-                ))
+                Err(ApiError::new(ApiErrorCode::NotFound, "Bridge transaction not found"))
             } else {
                 tracing::error!("Failed to query bridge status: {}", e);
-                Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: "Failed to query bridge status".to_string(),
-                        code: "QUERY_FAILED".to_string(),
-                    }),
-                ))
+                Err(ApiError::new(ApiErrorCode::QueryFailed, "Failed to query bridge status"))
             }
         }
     }
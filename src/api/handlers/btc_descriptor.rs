@@ -0,0 +1,140 @@
+//! Bitcoin xpub / output descriptor 导出（供外部 watch-only 监控）
+//!
+//! 只需要 `read_balance` 范围，但 xpub 会暴露整个账户的address关联，因此每次
+//! 导出都写入审计日志。wallet Password通过 `X-Wallet-Password` 头传入（GET 没有
+//! body），xpriv 只在派生过程中存在于内存，不会返回也不会落盘。
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::request_metrics::note_wallet_error;
+use crate::api::middleware::wallet_scope::extract_wallet_caller;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ParamError, ValidPath, ValidQuery, Validate, WalletNameParam};
+use crate::blockchain::bitcoin::AddressType;
+use crate::core::errors::WalletError;
+use crate::core::wallet_manager::btc_descriptor::{parse_address_type, BitcoinDescriptorExport};
+use crate::storage::WalletCapability;
+
+/// 携带wallet Password的请求头
+pub const WALLET_PASSWORD_HEADER: &str = "x-wallet-password";
+
+#[derive(Deserialize)]
+pub struct BtcDescriptorQuery {
+    /// legacy / segwit / taproot，默认 segwit
+    #[serde(rename = "type")]
+    pub address_type: Option<String>,
+    /// 默认 0
+    pub account: Option<u32>,
+}
+
+/// [`BtcDescriptorQuery`] validate后
+pub struct BtcDescriptorParams {
+    pub address_type: AddressType,
+    pub account: u32,
+}
+
+impl Validate for BtcDescriptorParams {
+    type Raw = BtcDescriptorQuery;
+
+    fn validate(raw: BtcDescriptorQuery) -> Result<Self, ParamError> {
+        let address_type = match raw.address_type.as_deref() {
+            Some(value) => parse_address_type(value).map_err(|_| ParamError::BtcAddressType)?,
+            None => AddressType::SegWit,
+        };
+        let account = raw.account.unwrap_or(0);
+        if account >= 1 << 31 {
+            return Err(ParamError::BtcAccount);
+        }
+        Ok(Self { address_type, account })
+    }
+}
+
+fn descriptor_error(e: WalletError) -> ApiError {
+    let (error, code) = match e {
+        WalletError::NotFoundError(msg) => (msg, ApiErrorCode::WalletNotFound),
+        WalletError::UnsupportedWalletKind(msg) => (msg, ApiErrorCode::UnsupportedWalletKind),
+        // decrypt_master_key：Password错误或不满足Password策略
+        WalletError::CryptoError(_) | WalletError::SecurityError(_) => {
+            ("Invalid wallet password".to_string(), ApiErrorCode::InvalidPassword)
+        }
+        WalletError::ValidationError(msg) => (msg, ApiErrorCode::InvalidRequest),
+        other => {
+            tracing::error!("btc descriptor export failed: {}", other);
+            note_wallet_error(&other);
+            ("Descriptor export failed".to_string(), ApiErrorCode::DescriptorFailed)
+        }
+    };
+    ApiError::new(code, error)
+}
+
+/// `GET /api/wallets/:name/btc/descriptor?type=segwit&account=0`
+pub async fn export_btc_descriptor(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(query): ValidQuery<BtcDescriptorParams>,
+) -> Result<Json<BitcoinDescriptorExport>, ApiError> {
+    let caller = extract_wallet_caller(&headers, &state).await?;
+    let name = name.as_str();
+    caller.authorize(&state, name, WalletCapability::ReadBalance).await?;
+
+    let password = headers
+        .get(WALLET_PASSWORD_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| Zeroizing::new(v.to_string()))
+        .ok_or(ParamError::Missing("X-Wallet-Password header"))?;
+
+    let export = state
+        .wallet_manager
+        .export_bitcoin_descriptor(name, &password, query.address_type, query.account)
+        .await
+        .map_err(descriptor_error)?;
+
+    caller.audit(&state, name, "btc_descriptor.export").await;
+    let details = serde_json::json!({
+        "user_id": caller.user_id(),
+        "address_type": export.address_type,
+        "account": export.account,
+        "fingerprint": export.fingerprint,
+    })
+    .to_string();
+    // 审计失败时不返回 xpub：导出必须可追溯
+    state.storage.log_action(name, "btc_descriptor.export", &details, None, None).await.map_err(|e| {
+        tracing::error!("failed to audit descriptor export for {}: {}", name, e);
+        descriptor_error(WalletError::StorageError(e.to_string()))
+    })?;
+
+    Ok(Json(export))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(address_type: Option<&str>, account: Option<u32>) -> BtcDescriptorQuery {
+        BtcDescriptorQuery { address_type: address_type.map(str::to_string), account }
+    }
+
+    #[test]
+    fn test_descriptor_params_defaults_and_bounds() {
+        let params = BtcDescriptorParams::validate(query(None, None)).unwrap();
+        assert_eq!(params.address_type, AddressType::SegWit);
+        assert_eq!(params.account, 0);
+
+        let params = BtcDescriptorParams::validate(query(Some("taproot"), Some(3))).unwrap();
+        assert_eq!(params.address_type, AddressType::Taproot);
+        assert_eq!(params.account, 3);
+
+        assert_eq!(BtcDescriptorParams::validate(query(Some("p2sh"), None)).err(), Some(ParamError::BtcAddressType));
+        assert_eq!(BtcDescriptorParams::validate(query(None, Some(1 << 31))).err(), Some(ParamError::BtcAccount));
+    }
+}
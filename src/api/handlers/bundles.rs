@@ -0,0 +1,345 @@
+//! operation bundle handlers
+//!
+//! 一次提交多步操作（approve → swap → bridge 等），按顺序由服务端sign执行；
+//! 后续步骤可用 `{{steps.<id>.amount}}` 引用前一步的实际产出。
+//!
+//! 限额按整个 bundle 在每个network上的原生币流出合计check，拆成多步绕不过
+//! token 单笔上限或审查阈值。超过审查阈值的 bundle 直接拒绝
+//! （403 `BUNDLE_REQUIRES_REVIEW`）：中途挂起的一步无法与其余步骤一起审批。
+//! 每步的状态与输出都会持久化；服务重启后仍在 running 的 bundle 标为
+//! `interrupted`，由 `POST /api/bundles/:id/resume` 从第一个未完成的步骤继续。
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, U256};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use super::approvals::approval_error;
+use super::bridge::initiate_bridge;
+use super::deadman::unlock_error;
+use super::key_usage::authorize_signing;
+use super::transaction::{
+    check_duplicate, check_spending_limit, guard_recipient, send_call_with_signer, send_with_signer,
+};
+use super::wallet_networks::check_network_allowed;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::wallet_scope::extract_wallet_caller;
+use crate::api::server::WalletServer;
+use crate::api::swap::handler::execute_swap;
+use crate::api::swap::SwapExecuteRequest;
+use crate::api::types::*;
+use crate::api::validators::{Amount, ParamError, ValidJson, ValidPath, Validate, WalletNameParam};
+use crate::intents::DecisionContext;
+use crate::blockchain::erc20::{
+    approval_amount, approve_calldata, decode_transfer, preflight, Erc20Error, TokenBehavior,
+};
+use crate::operations::{AmountSpec, Bundle, BundleRunError, StepAction, StepExecutor, StepFailure, StepOutput};
+use crate::pricing::native_symbol;
+use crate::storage::{BundleRecord, NewTokenDelivery, WalletCapability};
+
+fn run_error(e: BundleRunError) -> ApiError {
+    let status = match &e {
+        BundleRunError::NotFound => StatusCode::NOT_FOUND,
+        BundleRunError::NotRunnable(_) | BundleRunError::Conflict(_) => StatusCode::CONFLICT,
+        BundleRunError::Blocked { .. } => StatusCode::FORBIDDEN,
+        BundleRunError::Corrupt(_) | BundleRunError::Storage(_) => {
+            error!("bundle storage error: {}", e);
+            return ApiError::from_code(StatusCode::INTERNAL_SERVER_ERROR, e.code(), "Failed to access bundles");
+        }
+    };
+    ApiError::from_code(status, e.code(), e.to_string())
+}
+
+/// handler 的错误响应原样记为该步骤的失败原因
+fn step_failed(e: ApiError) -> StepFailure {
+    StepFailure::new(e.code().as_str(), e.message())
+}
+
+fn invalid_step(e: impl std::fmt::Display) -> StepFailure {
+    StepFailure::new("INVALID_STEP", e.to_string())
+}
+
+/// 已替换占位符的数量（十进制整单位）
+fn literal(amount: &AmountSpec) -> Result<String, StepFailure> {
+    amount.value().map(|d| d.normalize().to_string()).map_err(invalid_step)
+}
+
+/// 用wallet密钥执行各类步骤：转账、swap 与 bridge 走与单笔 API 相同的路径，
+/// approve 与合约调用经由sign意图日志
+struct ServerStepExecutor<'a> {
+    state: &'a WalletServer,
+    wallet_name: &'a str,
+    password: &'a str,
+    /// 提交时整个 bundle 通过的check；每步复制一份再补上本步的check
+    decision: DecisionContext,
+}
+
+impl ServerStepExecutor<'_> {
+    /// 每步都重新check network白名单与密钥使用策略
+    async fn signer(&self, network: &str, decision: &mut DecisionContext) -> Result<LocalWallet, StepFailure> {
+        let allowed = check_network_allowed(self.state, self.wallet_name, network).map_err(step_failed)?;
+        decision.record_network(network, allowed);
+        let signer = self
+            .state
+            .wallet_manager
+            .ethereum_signer(self.wallet_name, self.password)
+            .await
+            .map_err(|e| step_failed(unlock_error(self.wallet_name, e)))?;
+        authorize_signing(self.state, self.wallet_name).await.map_err(step_failed)?;
+        Ok(signer)
+    }
+
+    async fn call(&self, network: &str, to: Address, value: U256, data: Bytes) -> Result<String, StepFailure> {
+        let mut decision = self.decision.clone();
+        let signer = self.signer(network, &mut decision).await?;
+        send_call_with_signer(self.state, self.wallet_name, network, &signer, to, value, data, Some(decision))
+            .await
+            .map_err(step_failed)
+    }
+
+    /// 已广播的transaction登记失败不影响本步结果，只是回执到达时不再check到账
+    async fn register_delivery(
+        &self,
+        network: &str,
+        tx_hash: &str,
+        token: Address,
+        recipient: Address,
+        sent: U256,
+        behavior: &TokenBehavior,
+    ) {
+        let delivery = NewTokenDelivery {
+            network,
+            tx_hash,
+            wallet_name: self.wallet_name,
+            token: &format!("{:?}", token),
+            recipient: &format!("{:?}", recipient),
+            sent: &sent.to_string(),
+            fee_on_transfer: behavior.fee_on_transfer,
+        };
+        if let Err(e) = self.state.storage.register_token_delivery(&delivery).await {
+            error!("failed to register token delivery check of {}: {}", tx_hash, e);
+        }
+    }
+
+    /// token 调用先以 eth_call 模拟：返回 false、缺少返回值或会 revert（黑名单、暂停）的
+    /// 调用不上链。节点查询失败时与收款方check一样降级为不check
+    async fn token_call(
+        &self,
+        network: &str,
+        token: Address,
+        data: Bytes,
+        behavior: &TokenBehavior,
+    ) -> Result<String, StepFailure> {
+        let mut decision = self.decision.clone();
+        let signer = self.signer(network, &mut decision).await?;
+        let oracle = self.state.gas_oracle.as_ref();
+        match preflight(oracle, network, signer.address(), token, data.clone(), behavior).await {
+            Ok(()) => {}
+            Err(Erc20Error::Rpc(e)) => warn!("token call preflight on {} skipped: {}", network, e),
+            Err(e) => return Err(StepFailure::new(e.code(), e.to_string())),
+        }
+        send_call_with_signer(self.state, self.wallet_name, network, &signer, token, U256::zero(), data, Some(decision))
+            .await
+            .map_err(step_failed)
+    }
+}
+
+#[async_trait]
+impl StepExecutor for ServerStepExecutor<'_> {
+    async fn execute(&self, step_id: &str, action: &StepAction) -> Result<StepOutput, StepFailure> {
+        info!("bundle step {} ({}) on wallet {}", step_id, action.kind(), self.wallet_name);
+        match action {
+            StepAction::Transfer { network, to, amount, .. } => {
+                let amount = literal(amount)?;
+                let value = ethers::utils::parse_ether(&amount).map_err(invalid_step)?;
+                // 审查阈值已按合计在提交时check，这里不再挂起单步
+                let mut decision = self.decision.clone();
+                check_duplicate(self.state, self.wallet_name, network, *to, value, false, &mut decision)
+                    .await
+                    .map_err(step_failed)?;
+                let _sends = self.state.storage.lock_sends(self.wallet_name, network).await;
+                check_spending_limit(self.state, self.wallet_name, network, value, 0, &mut decision)
+                    .await
+                    .map_err(step_failed)?;
+                let signer = self.signer(network, &mut decision).await?;
+                let decision = Some(decision);
+                let tx_hash = send_with_signer(self.state, self.wallet_name, network, &signer, *to, value, decision)
+                    .await
+                    .map_err(step_failed)?;
+                Ok(StepOutput {
+                    tx_hash: Some(tx_hash),
+                    amount: Some(amount),
+                    asset: native_symbol(network).map(str::to_string),
+                    network: Some(network.clone()),
+                    ..Default::default()
+                })
+            }
+            StepAction::Approve { network, token, spender, amount, decimals, unlimited } => {
+                let units = ethers::utils::parse_units(literal(amount)?, *decimals).map_err(invalid_step)?;
+                // 标记 max_approval_disallowed 的 token 只授权所需数额
+                let behavior = self.state.tokens.behavior(network, *token).await;
+                let allowance = approval_amount(units.into(), *unlimited, &behavior);
+                let tx_hash = self.token_call(network, *token, approve_calldata(*spender, allowance), &behavior).await?;
+                Ok(StepOutput { tx_hash: Some(tx_hash), network: Some(network.clone()), ..Default::default() })
+            }
+            StepAction::ContractCall { network, to, data, value } => {
+                let value = match value {
+                    Some(value) => ethers::utils::parse_ether(literal(value)?).map_err(invalid_step)?,
+                    None => U256::zero(),
+                };
+                // ERC-20 transfer 按 token 行为模拟，需要时登记到账check
+                let tx_hash = match decode_transfer(data).filter(|_| value.is_zero()) {
+                    Some((recipient, sent)) => {
+                        let behavior = self.state.tokens.behavior(network, *to).await;
+                        let tx_hash = self.token_call(network, *to, data.clone(), &behavior).await?;
+                        if behavior.verify_delivery {
+                            self.register_delivery(network, &tx_hash, *to, recipient, sent, &behavior).await;
+                        }
+                        tx_hash
+                    }
+                    None => self.call(network, *to, value, data.clone()).await?,
+                };
+                Ok(StepOutput { tx_hash: Some(tx_hash), network: Some(network.clone()), ..Default::default() })
+            }
+            StepAction::Swap { network, from_token, to_token, amount, slippage } => {
+                let request = SwapExecuteRequest {
+                    wallet_name: self.wallet_name.to_string(),
+                    from_token: from_token.clone(),
+                    to_token: to_token.clone(),
+                    amount: literal(amount)?,
+                    network: network.clone(),
+                    slippage: *slippage,
+                    password: Some(self.password.to_string()),
+                    client_request_id: None,
+                };
+                let swapped = execute_swap(self.state, &request).await.map_err(step_failed)?;
+                Ok(StepOutput {
+                    tx_hash: Some(swapped.tx_id),
+                    amount: Some(swapped.to_amount),
+                    asset: Some(to_token.to_uppercase()),
+                    network: Some(network.clone()),
+                    ..Default::default()
+                })
+            }
+            StepAction::Bridge { from_chain, to_chain, token, amount } => {
+                let payload = BridgeAssets::validate(BridgeAssetsRequest {
+                    from_wallet: self.wallet_name.to_string(),
+                    from_chain: from_chain.clone(),
+                    to_chain: to_chain.clone(),
+                    token: token.clone(),
+                    amount: literal(amount)?,
+                    client_request_id: None,
+                })
+                .map_err(invalid_step)?;
+                let bridged = initiate_bridge(self.state, &payload).await.map_err(step_failed)?;
+                Ok(StepOutput {
+                    reference: Some(bridged.bridge_id),
+                    amount: bridged.amount,
+                    asset: Some(token.to_uppercase()),
+                    network: Some(to_chain.clone()),
+                    ..Default::default()
+                })
+            }
+        }
+    }
+}
+
+/// `POST /api/wallets/:name/bundles`：校验、筛查后同步执行，返回各步骤结果
+pub async fn submit_bundle(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(params): ValidJson<BundleSubmission>,
+) -> Result<Response, ApiError> {
+    let name = name.as_str();
+    let caller = extract_wallet_caller(&headers, &state).await?;
+    caller.authorize(&state, name, WalletCapability::Send).await?;
+    caller.audit(&state, name, "bundle").await;
+    let mut decision = DecisionContext::new();
+    decision.record_requester(caller.user_id());
+    caller.record_token_limit(&mut decision);
+
+    let bundle = Bundle::builder(name)
+        .failure_policy(params.failure_policy)
+        .steps(params.steps)
+        .build()
+        .map_err(|e| ParamError::Bundle(e.to_string()))?;
+    for network in bundle.networks() {
+        check_network_allowed(&state, name, network)?;
+    }
+    // 普通转账的收款方check与单笔发送一致
+    for step in &bundle.steps {
+        if let StepAction::Transfer { network, to, acknowledge_contract_recipient, .. } = &step.action {
+            if let Err(refused) = guard_recipient(&state, network, *to, *acknowledge_contract_recipient).await {
+                return Ok(refused);
+            }
+        }
+    }
+    for (network, total) in bundle.native_outflow() {
+        let total = Amount::try_from(total.normalize().to_string().as_str())?;
+        caller.check_amount(&total)?;
+        let review = state.approvals.review(name, total.as_str()).await.map_err(approval_error)?;
+        if review.required {
+            return Err(ApiError::new(
+                ApiErrorCode::BundleRequiresReview,
+                format!(
+                    "Bundle sends {} on {} in total, above the wallet's review threshold; bundles cannot be held \
+                     for approval",
+                    total, network
+                ),
+            ));
+        }
+        decision.record_review(review);
+    }
+    state.bundles.screen(&bundle, &mut decision).await.map_err(run_error)?;
+    // Password错误时不留下 bundle 记录
+    state.wallet_manager.ethereum_signer(name, &params.password).await.map_err(|e| unlock_error(name, e))?;
+
+    let id = state.bundles.submit(&bundle, caller.user_id(), caller.token_id()).await.map_err(run_error)?;
+    decision.record_bundle(&id);
+    let executor = ServerStepExecutor { state: &state, wallet_name: name, password: &params.password, decision };
+    let record = state.bundles.run(&id, &executor).await.map_err(run_error)?;
+    Ok(Json(record).into_response())
+}
+
+/// `GET /api/bundles/:id`：需要该wallet的 `read_history`
+pub async fn get_bundle(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<BundleRecord>, ApiError> {
+    let caller = extract_wallet_caller(&headers, &state).await?;
+    let record = state.bundles.get(&id).await.map_err(run_error)?;
+    caller.authorize(&state, &record.wallet_name, WalletCapability::ReadHistory).await?;
+    Ok(Json(record))
+}
+
+/// `POST /api/bundles/:id/resume`：只接受 `interrupted` 的 bundle，已完成的步骤不会重复执行
+pub async fn resume_bundle(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    ValidJson(params): ValidJson<BundleResume>,
+) -> Result<Json<BundleRecord>, ApiError> {
+    let caller = extract_wallet_caller(&headers, &state).await?;
+    let record = state.bundles.get(&id).await.map_err(run_error)?;
+    let name = record.wallet_name.as_str();
+    caller.authorize(&state, name, WalletCapability::Send).await?;
+    caller.audit(&state, name, "bundle.resume").await;
+    state.wallet_manager.ethereum_signer(name, &params.password).await.map_err(|e| unlock_error(name, e))?;
+
+    // 提交时的筛查与合计check不重做，恢复的步骤只记录本次请求方与逐步check
+    let mut decision = DecisionContext::new();
+    decision.record_requester(caller.user_id());
+    caller.record_token_limit(&mut decision);
+    decision.record_bundle(&id);
+    let executor = ServerStepExecutor { state: &state, wallet_name: name, password: &params.password, decision };
+    let record = state.bundles.resume(&id, &executor).await.map_err(run_error)?;
+    Ok(Json(record))
+}
@@ -0,0 +1,225 @@
+//! 托管wallet（API key）：由服务端保存加密主密钥的wallet
+//!
+//! `hot_wallet client` 用这组接口管理wallet；发送走 `POST /api/transactions/send`。
+//! wallet Password只在请求体里传，用完即丢，不落库也不写日志。
+
+use axum::{extract::State, http::HeaderMap, response::Json};
+use chrono::{DateTime, Utc};
+use ethers::signers::Signer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::backup::persist_restored;
+use super::wallet_networks::check_network_allowed;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{
+    validate_password_strength, NetworkName, ParamError, Validate, ValidJson, ValidPath, WalletNameParam,
+};
+use crate::core::errors::WalletError;
+
+/// `GET /api/admin/wallets` 中的一项
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CustodialWalletSummary {
+    pub name: String,
+    pub quantum_safe: bool,
+    pub created_at: DateTime<Utc>,
+    /// 未限制时省略，表示所有network可用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_networks: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CustodialWalletList {
+    pub wallets: Vec<CustodialWalletSummary>,
+}
+
+/// `POST /api/admin/wallets` 请求体
+#[derive(Deserialize)]
+pub struct CreateCustodialWalletRequest {
+    pub name: String,
+    pub password: String,
+    #[serde(default)]
+    pub quantum_safe: bool,
+}
+
+// Password不进 Debug 输出
+impl std::fmt::Debug for CreateCustodialWalletRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CreateCustodialWalletRequest")
+            .field("name", &self.name)
+            .field("quantum_safe", &self.quantum_safe)
+            .finish_non_exhaustive()
+    }
+}
+
+/// [`CreateCustodialWalletRequest`] validate后
+pub struct CreateCustodialWallet {
+    pub name: WalletNameParam,
+    pub password: String,
+    pub quantum_safe: bool,
+}
+
+impl Validate for CreateCustodialWallet {
+    type Raw = CreateCustodialWalletRequest;
+
+    fn validate(raw: CreateCustodialWalletRequest) -> Result<Self, ParamError> {
+        if raw.password.is_empty() {
+            return Err(ParamError::Missing("password"));
+        }
+        Ok(Self {
+            name: WalletNameParam::try_from(raw.name.as_str())?,
+            password: raw.password,
+            quantum_safe: raw.quantum_safe,
+        })
+    }
+}
+
+/// 创建结果；mnemonic不返回，需要时走 `GET /api/wallets/:name/backup`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CustodialWalletCreated {
+    pub name: String,
+    pub address: String,
+    pub quantum_safe: bool,
+}
+
+/// `POST /api/admin/wallets/:name/balance` 请求体；Password用于解出address，所以不放在 query 里
+#[derive(Deserialize)]
+pub struct CustodialBalanceRequest {
+    #[serde(default = "default_network")]
+    pub network: String,
+    pub password: String,
+}
+
+impl std::fmt::Debug for CustodialBalanceRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustodialBalanceRequest").field("network", &self.network).finish_non_exhaustive()
+    }
+}
+
+fn default_network() -> String {
+    "eth".to_string()
+}
+
+/// [`CustodialBalanceRequest`] validate后；只支持 EVM network
+pub struct CustodialBalance {
+    pub network: NetworkName,
+    pub password: String,
+}
+
+impl Validate for CustodialBalance {
+    type Raw = CustodialBalanceRequest;
+
+    fn validate(raw: CustodialBalanceRequest) -> Result<Self, ParamError> {
+        if raw.password.is_empty() {
+            return Err(ParamError::Missing("password"));
+        }
+        Ok(Self { network: NetworkName::try_from(raw.network.as_str())?.require_evm()?, password: raw.password })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CustodialBalanceResponse {
+    pub name: String,
+    pub network: String,
+    pub address: String,
+    pub balance: String,
+}
+
+fn unauthorized() -> ApiError {
+    ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
+}
+
+fn custodial_error(e: WalletError) -> ApiError {
+    match e {
+        // decrypt_master_key: wallet Password错误
+        WalletError::CryptoError(_) | WalletError::InvalidPassword(_) => {
+            ApiError::new(ApiErrorCode::InvalidPassword, "Invalid wallet password")
+        }
+        other => other.into(),
+    }
+}
+
+/// `GET /api/admin/wallets`：按名称排序
+pub async fn list_custodial_wallets(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+) -> Result<Json<CustodialWalletList>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let mut wallets: Vec<CustodialWalletSummary> = state
+        .wallet_manager
+        .list_wallets()
+        .await
+        .map_err(custodial_error)?
+        .into_iter()
+        .map(|info| CustodialWalletSummary {
+            name: info.name,
+            quantum_safe: info.quantum_safe,
+            created_at: info.created_at,
+            allowed_networks: info.allowed_networks,
+        })
+        .collect();
+    wallets.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Json(CustodialWalletList { wallets }))
+}
+
+/// `POST /api/admin/wallets`
+pub async fn create_custodial_wallet(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidJson(req): ValidJson<CreateCustodialWallet>,
+) -> Result<Json<CustodialWalletCreated>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    validate_password_strength(&req.password)?;
+
+    let name = req.name.as_str();
+    if state.wallet_manager.get_wallet_by_name(name).await.map_err(custodial_error)?.is_some() {
+        return Err(ApiError::new(ApiErrorCode::WalletExists, format!("Wallet '{}' already exists", name)));
+    }
+    state.wallet_manager.create_wallet(name, &req.password, req.quantum_safe).await.map_err(custodial_error)?;
+    // 与恢复的wallet一样落库，重启后仍可加载；落库失败则撤销，避免只存在于内存的wallet
+    if let Err(e) = persist_restored(&state, name, req.quantum_safe, None).await {
+        error!("failed to persist custodial wallet {}: {}", name, e);
+        if let Err(e) = state.wallet_manager.delete_wallet(name).await {
+            error!("failed to discard unpersisted custodial wallet {}: {}", name, e);
+        }
+        return Err(ApiError::generic(ApiErrorCode::DbError));
+    }
+    let address = state.wallet_manager.ethereum_signer(name, &req.password).await.map_err(custodial_error)?.address();
+    info!("custodial wallet created: {}", name);
+
+    Ok(Json(CustodialWalletCreated {
+        name: name.to_string(),
+        address: format!("{:#x}", address),
+        quantum_safe: req.quantum_safe,
+    }))
+}
+
+/// `POST /api/admin/wallets/:name/balance`：native 币balance，经 `chain_clients` 查询
+pub async fn custodial_wallet_balance(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(req): ValidJson<CustodialBalance>,
+) -> Result<Json<CustodialBalanceResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let name = name.as_str();
+    let network = req.network.as_str();
+    check_network_allowed(&state, name, network)?;
+    let address = state.wallet_manager.ethereum_signer(name, &req.password).await.map_err(custodial_error)?.address();
+    let address = format!("{:#x}", address);
+
+    let client =
+        state.chain_clients.get(network).map_err(|e| ApiError::new(ApiErrorCode::NetworkUnavailable, e.to_string()))?;
+    let balance = client.get_balance(&address).await.map_err(|e| {
+        error!("custodial balance query failed: wallet={}, network={}, error={}", name, network, e);
+        ApiError::new(ApiErrorCode::BlockchainQueryFailed, "Failed to get balance from blockchain")
+    })?;
+
+    Ok(Json(CustodialBalanceResponse { name: name.to_string(), network: network.to_string(), address, balance }))
+}
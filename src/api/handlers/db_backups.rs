@@ -0,0 +1,65 @@
+//! 数据库备份运维接口（API key）
+//!
+//! 备份本身由 `ops::db_backup::BackupScheduler` 定时执行；这里只提供历史查询
+//! 与手动触发。手动触发与定时任务共用同一把锁，不会并发写两个备份。
+
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+use super::admin::unauthorized;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+
+/// 历史记录单次最多返回条数
+pub const MAX_BACKUP_HISTORY: i64 = 500;
+const DEFAULT_BACKUP_HISTORY: i64 = 50;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BackupHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+/// `GET /api/admin/backups?limit=50`：最近的备份记录（含failed与已清理的）
+pub async fn list_backups(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Query(query): Query<BackupHistoryQuery>,
+) -> Result<Json<BackupHistoryResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_BACKUP_HISTORY).clamp(1, MAX_BACKUP_HISTORY);
+    let backups = state.storage.backup_history(limit).await.map_err(|e| {
+        error!("backup history query failed: {}", e);
+        ApiError::new(ApiErrorCode::DbError, "Failed to load backup history")
+    })?;
+
+    Ok(Json(BackupHistoryResponse {
+        enabled: state.backups.config().enabled,
+        interval_secs: state.backups.config().interval_secs,
+        backups,
+    }))
+}
+
+/// `POST /api/admin/backups/run`：立即执行一次备份（含validate与清理）
+///
+/// 备份failed时仍返回 200，结果写在 `status` / `error` 中，与历史记录一致。
+pub async fn run_backup(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+) -> Result<Json<BackupRunResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let report = state.backups.try_run().await.map_err(|e| {
+        ApiError::new(ApiErrorCode::BackupInProgress, e.to_string())
+    })?;
+    Ok(Json(report))
+}
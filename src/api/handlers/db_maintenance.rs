@@ -0,0 +1,60 @@
+//! 数据库维护接口（API key）：WAL checkpoint 与在线快照
+//!
+//! checkpoint 由 `ops::wal::WalMaintenance` 定时执行，这里的手动触发跳过
+//! 连接繁忙检查，但仍会在读事务阻塞时重试。`vacuum-into` 与备份任务共用
+//! 同一把锁和同一个 sink，不记入备份历史。
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use std::sync::Arc;
+use tracing::error;
+
+use super::admin::unauthorized;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::ops::db_backup::check_key;
+use crate::ops::wal::CheckpointError;
+
+/// `POST /api/admin/db/checkpoint`：立即执行一次 `wal_checkpoint(TRUNCATE)`
+///
+/// 读事务一直占着旧快照时返回 200，`outcome.busy` 为 true。
+pub async fn db_checkpoint(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+) -> Result<Json<DbCheckpointResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    match state.wal.try_checkpoint(true).await {
+        Ok(report) => Ok(Json(report)),
+        Err(e @ CheckpointError::InProgress) => Err(ApiError::new(ApiErrorCode::CheckpointInProgress, e.to_string())),
+        Err(CheckpointError::Failed(e)) => {
+            error!("manual WAL checkpoint failed: {:#}", e);
+            Err(ApiError::new(ApiErrorCode::CheckpointFailed, "WAL checkpoint failed"))
+        }
+    }
+}
+
+/// `POST /api/admin/db/vacuum-into`：`VACUUM INTO` 在线快照写入备份 sink
+///
+/// 写入或validatefailed时仍返回 200，结果写在 `verified` / `error` 中，与手动备份一致。
+pub async fn db_vacuum_into(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(request): Json<VacuumIntoRequest>,
+) -> Result<Json<VacuumIntoResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    check_key(&request.object_key).map_err(|e| {
+        ApiError::new(ApiErrorCode::InvalidObjectKey, e.to_string())
+    })?;
+
+    let report = state.backups.try_vacuum_into(&request.object_key).await.map_err(|e| {
+        ApiError::new(ApiErrorCode::BackupInProgress, e.to_string())
+    })?;
+    Ok(Json(report))
+}
@@ -0,0 +1,178 @@
+//! dead-man's switch handlers
+//!
+//! 配置时用wallet Password解出sign密钥，立即密封为只能转给受益人的sweep
+//! 能力（[`SweepCapability`]），之后触发不再需要Password。签到可以显式调用
+//! checkin，也在每次服务端sign时由 [`implicit_checkin`] 隐式完成。
+//! 到期后的警告与归集由 `ops::deadman` 的后台任务执行。
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use std::sync::Arc;
+use tracing::{error, warn};
+use zeroize::Zeroizing;
+
+use super::wallet_networks::check_network_allowed;
+use super::wallet_tokens::ADMIN_ISSUER;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::authorize_owner_or_admin;
+use crate::api::middleware::request_metrics::{note_storage_error, note_wallet_error};
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ValidJson, ValidPath, WalletNameParam};
+use crate::core::errors::WalletError;
+use crate::security::sweep_capability::SweepCapability;
+use crate::storage::NewDeadmanPolicy;
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("dead-man policy storage failed: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access dead-man policy")
+}
+
+/// 解锁wallet密钥的错误：不存在为 404，Password错误为 401 `INVALID_PASSWORD`
+pub(crate) fn unlock_error(name: &str, e: WalletError) -> ApiError {
+    match e {
+        WalletError::NotFoundError(_) => ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found"),
+        WalletError::CryptoError(_) | WalletError::ValidationError(_) | WalletError::SecurityError(_) => {
+            ApiError::new(ApiErrorCode::InvalidPassword, "Invalid wallet password")
+        }
+        other => {
+            error!("unlocking {} failed: {}", name, other);
+            note_wallet_error(&other);
+            ApiError::new(ApiErrorCode::SigningKeyUnavailable, "Failed to unlock wallet")
+        }
+    }
+}
+
+/// 解出wallet sign密钥
+async fn unlock(state: &WalletServer, name: &str, password: &str) -> Result<ethers::signers::LocalWallet, ApiError> {
+    state.wallet_manager.ethereum_signer(name, password).await.map_err(|e| unlock_error(name, e))
+}
+
+async fn audit(state: &WalletServer, name: &str, action: &str, details: serde_json::Value) {
+    if let Err(e) = state.storage.log_action(name, action, &details.to_string(), None, None).await {
+        error!("failed to audit {} on {}: {}", action, name, e);
+    }
+}
+
+/// 服务端sign即视为签到；失败只记录日志，不阻断sign
+pub(crate) async fn implicit_checkin(state: &WalletServer, wallet_name: &str) {
+    if let Err(e) = state.storage.deadman_checkin(wallet_name).await {
+        warn!("dead-man check-in for {} failed: {}", wallet_name, e);
+    }
+}
+
+/// `POST /api/wallets/:name/deadman`：wallet owner 或 admin
+///
+/// 覆盖已有策略并从现在重新计时；已触发的策略也会重新武装。
+pub async fn put_deadman_policy(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<DeadmanPolicyConfig>,
+) -> Result<Json<DeadmanPolicyResponse>, ApiError> {
+    let name = name.as_str();
+    let user_id = authorize_owner_or_admin(&headers, &state, name).await?;
+    let min_inactivity = state.config.security.deadman.min_inactivity_secs;
+    if payload.inactivity_secs < min_inactivity {
+        return Err(ApiError::new(
+            ApiErrorCode::InvalidDeadmanPolicy,
+            format!("inactivity_secs must be at least {}", min_inactivity),
+        ));
+    }
+    let networks: Vec<String> = payload.networks.iter().map(|n| n.as_str().to_string()).collect();
+    for network in &networks {
+        check_network_allowed(&state, name, network)?;
+    }
+
+    let signer = unlock(&state, name, &payload.password).await?;
+    let secret = Zeroizing::new(signer.signer().to_bytes().to_vec());
+    let capability = SweepCapability::seal(payload.beneficiary.as_str(), &networks, &secret).map_err(|e| {
+        error!("sealing dead-man capability for {} failed: {}", name, e);
+        ApiError::new(ApiErrorCode::EncryptionFailed, "Failed to seal sweep capability")
+    })?;
+
+    let policy = state
+        .storage
+        .put_deadman_policy(&NewDeadmanPolicy {
+            wallet_name: name,
+            beneficiary: payload.beneficiary.as_str(),
+            networks: &networks,
+            inactivity_secs: payload.inactivity_secs,
+            warning_secs: &payload.warning_secs,
+            capability: &capability,
+        })
+        .await
+        .map_err(storage_error)?;
+    audit(
+        &state,
+        name,
+        "deadman.configured",
+        serde_json::json!({
+            "beneficiary": policy.beneficiary,
+            "networks": policy.networks,
+            "inactivity_secs": policy.inactivity_secs,
+            "warning_secs": policy.warning_secs,
+            "by": user_id.as_deref().unwrap_or(ADMIN_ISSUER),
+        }),
+    )
+    .await;
+    Ok(Json(policy.into()))
+}
+
+/// `GET /api/wallets/:name/deadman`：wallet owner 或 admin
+pub async fn get_deadman_policy(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<DeadmanPolicyResponse>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    match state.storage.deadman_policy(name).await.map_err(storage_error)? {
+        Some(policy) => Ok(Json(policy.into())),
+        None => Err(ApiError::new(ApiErrorCode::DeadmanNotFound, "No dead-man policy for this wallet")),
+    }
+}
+
+/// `POST /api/wallets/:name/deadman/checkin`：wallet owner 或 admin
+///
+/// 已触发的策略不再接受签到（409 `DEADMAN_TRIGGERED`）。
+pub async fn deadman_checkin(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<DeadmanPolicyResponse>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    let checked_in = state.storage.deadman_checkin(name).await.map_err(storage_error)?;
+    let policy = state.storage.deadman_policy(name).await.map_err(storage_error)?;
+    match policy {
+        None => Err(ApiError::new(ApiErrorCode::DeadmanNotFound, "No dead-man policy for this wallet")),
+        Some(_) if !checked_in => Err(ApiError::new(
+            ApiErrorCode::DeadmanTriggered,
+            "The dead-man switch has already been triggered",
+        )),
+        Some(policy) => Ok(Json(policy.into())),
+    }
+}
+
+/// `DELETE /api/wallets/:name/deadman`：wallet owner 或 admin，且需要wallet Password
+pub async fn cancel_deadman_policy(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    Json(payload): Json<DeadmanCancelRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let name = name.as_str();
+    let user_id = authorize_owner_or_admin(&headers, &state, name).await?;
+    unlock(&state, name, &payload.password).await?;
+    if !state.storage.delete_deadman_policy(name).await.map_err(storage_error)? {
+        return Err(ApiError::new(ApiErrorCode::DeadmanNotFound, "No dead-man policy for this wallet"));
+    }
+    audit(&state, name, "deadman.cancelled", serde_json::json!({ "by": user_id.as_deref().unwrap_or(ADMIN_ISSUER) }))
+        .await;
+    Ok(Json(serde_json::json!({ "wallet_name": name, "cancelled": true })))
+}
@@ -0,0 +1,335 @@
+//! 委托会话 handlers
+//!
+//! wallet owner 用Password创建委托：每个network一个总预算（最小单位）、单笔上限、
+//! 有效期和可选的收款方白名单，返回只显示一次的 `dlg_` token。自动化代理持该
+//! token 只能调用这个wallet的 send 端点，不需要Password：sign密钥在创建时
+//! 密封为 [`DelegationKey`]，只有出示 token 的请求才能打开。
+//!
+//! 预算在sign前原子预留（见 `storage::delegations`），并发发送不会超支；
+//! 广播前失败的发送退回预留额度，广播结果不确定的不退。
+
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::{IntoResponse, Json, Response},
+};
+use ethers::signers::LocalWallet;
+use ethers::types::{Address, U256};
+use rand::RngCore;
+use std::sync::Arc;
+use tracing::{error, warn};
+use zeroize::Zeroizing;
+
+use super::approvals::approval_error;
+use super::deadman::unlock_error;
+use super::key_usage::authorize_signing;
+use super::transaction::{
+    check_duplicate, check_spending_limit, guard_recipient, screen_send, send_conflict, send_failed,
+};
+use super::wallet_networks::check_network_allowed;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::{
+    authorize_owner_or_admin, extract_user_id_from_token, verify_wallet_ownership,
+};
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::middleware::wallet_scope::DELEGATION_TOKEN_PREFIX;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ValidJson, ValidPath, WalletNameParam};
+use crate::intents::{DecisionContext, IntentError};
+use crate::security::delegation_key::DelegationKey;
+use crate::storage::{DelegationRecord, NewDelegation, Reservation};
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("delegation storage failed: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access delegations")
+}
+
+fn not_found() -> ApiError {
+    ApiError::new(ApiErrorCode::DelegationNotFound, "Delegation not found")
+}
+
+async fn audit(state: &WalletServer, name: &str, action: &str, details: serde_json::Value) {
+    if let Err(e) = state.storage.log_action(name, action, &details.to_string(), None, None).await {
+        error!("failed to audit {} on {}: {}", action, name, e);
+    }
+}
+
+/// `POST /api/wallets/:name/delegations`：仅wallet owner（会话 token），需要Password
+pub async fn create_delegation(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<CreateDelegation>,
+) -> Result<Json<DelegationCreatedResponse>, ApiError> {
+    let name = name.as_str();
+    let user_id = extract_user_id_from_token(&headers, &state).await?;
+    verify_wallet_ownership(&user_id, name, &state).await?;
+    let budgets: Vec<(String, U256)> =
+        payload.budgets.iter().map(|(network, total)| (network.as_str().to_string(), *total)).collect();
+    for (network, _) in &budgets {
+        check_network_allowed(&state, name, network)?;
+    }
+
+    let signer =
+        state.wallet_manager.ethereum_signer(name, &payload.password).await.map_err(|e| unlock_error(name, e))?;
+    let mut secret = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut secret);
+    let token = format!("{}{}", DELEGATION_TOKEN_PREFIX, hex::encode(secret));
+    let id = state.storage.id_generator().new_id();
+    let key = Zeroizing::new(signer.signer().to_bytes().to_vec());
+    let key = DelegationKey::seal(&id, &token, &key).map_err(|e| {
+        error!("sealing delegation key for {} failed: {}", name, e);
+        ApiError::new(ApiErrorCode::EncryptionFailed, "Failed to seal delegation key")
+    })?;
+
+    let expires_at = state.storage.clock().now().timestamp() + payload.expires_in_secs as i64;
+    let delegation = state
+        .storage
+        .insert_delegation(&NewDelegation {
+            id: &id,
+            token_hash: &crate::storage::hash_token(&token),
+            wallet_name: name,
+            owner_user_id: &user_id,
+            per_tx_cap: payload.per_tx_cap,
+            recipients: payload.recipients.as_deref(),
+            budgets: &budgets,
+            expires_at,
+            created_by: &user_id,
+            key: &key,
+        })
+        .await
+        .map_err(storage_error)?;
+    audit(
+        &state,
+        name,
+        "delegation.created",
+        serde_json::json!({
+            "delegation_id": id,
+            "budgets": delegation.budgets,
+            "per_tx_cap": delegation.per_tx_cap,
+            "recipients": delegation.recipients,
+            "expires_at": expires_at,
+            "created_by": user_id,
+        }),
+    )
+    .await;
+    Ok(Json(DelegationCreatedResponse { token, delegation }))
+}
+
+/// `GET /api/wallets/:name/delegations`：wallet owner 或 admin，含已吊销和已过期的
+pub async fn list_delegations(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<DelegationListResponse>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    let delegations = state.storage.delegations_for(name).await.map_err(storage_error)?;
+    Ok(Json(DelegationListResponse { delegations }))
+}
+
+/// `GET /api/wallets/:name/delegations/:id`：各network的剩余预算
+pub async fn get_delegation(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((name, id)): Path<(String, String)>,
+) -> Result<Json<DelegationRecord>, ApiError> {
+    let name = WalletNameParam::try_from(name.as_str())?;
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    state.storage.delegation(name, &id).await.map_err(storage_error)?.map(Json).ok_or_else(not_found)
+}
+
+/// `DELETE /api/wallets/:name/delegations/:id`：立即生效，进行中的预留不受影响
+pub async fn revoke_delegation(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((name, id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let name = WalletNameParam::try_from(name.as_str())?;
+    let name = name.as_str();
+    let user_id = authorize_owner_or_admin(&headers, &state, name).await?;
+    if !state.storage.revoke_delegation(name, &id).await.map_err(storage_error)? {
+        return Err(not_found());
+    }
+    audit(
+        &state,
+        name,
+        "delegation.revoked",
+        serde_json::json!({
+            "delegation_id": id,
+            "revoked_by": user_id.as_deref().unwrap_or(super::wallet_tokens::ADMIN_ISSUER),
+        }),
+    )
+    .await;
+    Ok(Json(serde_json::json!({ "success": true, "message": "Delegation revoked" })))
+}
+
+fn expired() -> ApiError {
+    ApiError::new(ApiErrorCode::DelegationExpired, "Delegation has expired")
+}
+
+/// 委托 token 的发送：由 `send_transaction` 在任何其他认证之前分派到这里
+///
+/// 单笔上限、白名单、network预算在预留前check；预留本身再确认未吊销、未过期、
+/// 余额足够，三者分别返回 `INVALID_TOKEN`、`DELEGATION_EXPIRED`、
+/// `DELEGATION_BUDGET_EXHAUSTED`。
+pub(crate) async fn send_delegated(
+    state: &WalletServer,
+    delegation: DelegationRecord,
+    token: &str,
+    wallet_name: &str,
+    network: &str,
+    payload: &SendTransaction,
+) -> Result<Response, ApiError> {
+    if delegation.wallet_name != wallet_name {
+        return Err(ApiError::new(
+            ApiErrorCode::DelegationScopeDenied,
+            "Forbidden: Delegation is not valid for this wallet",
+        ));
+    }
+    if delegation.is_expired(state.storage.clock().now().timestamp()) {
+        return Err(expired());
+    }
+    if payload.signed_tx.is_some() {
+        return Err(ApiError::new(
+            ApiErrorCode::DelegationSignedTxUnsupported,
+            "Delegated sends are signed by the server; omit signed_tx",
+        ));
+    }
+    let mut decision = DecisionContext::new();
+    let allowed = check_network_allowed(state, wallet_name, network)?;
+    decision.record_network(network, allowed);
+
+    // 两者均已validate
+    let to: Address = payload.to.as_str().parse().map_err(|e| send_failed(&e))?;
+    let value = ethers::utils::parse_ether(payload.amount.as_str()).map_err(|e| send_failed(&e))?;
+    if delegation.budget(network).is_none() {
+        return Err(ApiError::new(
+            ApiErrorCode::DelegationNetworkDenied,
+            format!("Delegation grants no budget on {}", network),
+        ));
+    }
+    if value > delegation.per_tx_cap_wei() {
+        return Err(ApiError::new(
+            ApiErrorCode::DelegationTxCapExceeded,
+            format!("Amount exceeds the delegation's per-transaction cap of {} wei", delegation.per_tx_cap),
+        ));
+    }
+    if !delegation.allows_recipient(payload.to.as_str()) {
+        return Err(ApiError::new(
+            ApiErrorCode::DelegationRecipientDenied,
+            "Recipient is not on the delegation's allowlist",
+        ));
+    }
+    match guard_recipient(state, network, to, payload.acknowledge_contract_recipient).await {
+        Ok(class) => decision.record_recipient(class),
+        Err(refused) => return Ok(refused),
+    }
+    // 委托发送无人值守，不能挂起等待审批
+    let review = state.approvals.review(wallet_name, payload.amount.as_str()).await.map_err(approval_error)?;
+    if review.required {
+        return Err(ApiError::new(
+            ApiErrorCode::DelegationReviewRequired,
+            "Amount exceeds the wallet's review threshold and cannot be sent under a delegation",
+        ));
+    }
+    decision.record_review(review);
+    check_duplicate(state, wallet_name, network, to, value, payload.allow_duplicate, &mut decision).await?;
+    let _sends = state.storage.lock_sends(wallet_name, network).await;
+    check_spending_limit(state, wallet_name, network, value, 0, &mut decision).await?;
+    let signer = delegation.key.open(&delegation.id, token).map_err(|e| {
+        error!("opening delegation key {} failed: {}", delegation.id, e);
+        ApiError::new(ApiErrorCode::SigningKeyUnavailable, "Failed to unlock delegation")
+    })?;
+
+    let recipient = format!("{:#x}", to);
+    let reservation =
+        state.storage.reserve_delegation_budget(&delegation, network, &recipient, value).await.map_err(storage_error)?;
+    match reservation {
+        Reservation::Reserved { total, spent } => {
+            decision.record_delegation(&delegation.id, &delegation.per_tx_cap, total, spent)
+        }
+        Reservation::Revoked => {
+            return Err(ApiError::new(ApiErrorCode::InvalidToken, "Unauthorized: Unknown or revoked token"))
+        }
+        Reservation::Expired => return Err(expired()),
+        Reservation::NoBudget => {
+            return Err(ApiError::new(
+                ApiErrorCode::DelegationNetworkDenied,
+                format!("Delegation grants no budget on {}", network),
+            ))
+        }
+        Reservation::Exhausted { remaining } => {
+            return Err(ApiError::new(
+                ApiErrorCode::DelegationBudgetExhausted,
+                format!("Delegation budget on {} is exhausted ({} wei remaining)", network, remaining),
+            ))
+        }
+    }
+
+    let tx_hash = match sign_and_send(state, wallet_name, network, &signer, to, value, decision).await {
+        Ok(tx_hash) => tx_hash,
+        Err((e, broadcast_attempted)) => {
+            if !broadcast_attempted {
+                refund(state, &delegation, network, value, e.code().as_str()).await;
+            }
+            return Err(e);
+        }
+    };
+    audit(
+        state,
+        wallet_name,
+        "delegation.send",
+        serde_json::json!({
+            "delegation_id": delegation.id,
+            "network": network,
+            "to": recipient,
+            "value": value.to_string(),
+            "tx_hash": tx_hash,
+        }),
+    )
+    .await;
+    Ok(Json(TransactionResponse {
+        tx_id: tx_hash.clone(),
+        explorer_url: state.config.blockchain.explorer_tx_url(network, &tx_hash),
+        tx_hash: Some(tx_hash),
+        status: "sent".to_string(),
+        network: network.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        fee: "0.0".to_string(),
+        confirmations: "0".to_string(),
+    })
+    .into_response())
+}
+
+/// 失败时同时返回交易是否可能已广播：广播失败或之后的存储错误都不退预算
+async fn sign_and_send(
+    state: &WalletServer,
+    wallet_name: &str,
+    network: &str,
+    signer: &LocalWallet,
+    to: Address,
+    value: U256,
+    mut decision: DecisionContext,
+) -> Result<String, (ApiError, bool)> {
+    screen_send(state, wallet_name, network, to, value, &mut decision).await.map_err(|e| (e, false))?;
+    authorize_signing(state, wallet_name).await.map_err(|e| (e, false))?;
+    let tx = ethers::types::TransactionRequest::new().to(to).value(value).into();
+    let sent = state.signing_intents.send_with_decision(wallet_name, network, signer, tx, Some(decision)).await;
+    sent.map_err(|e| match e {
+        e @ (IntentError::NonceInUse { .. } | IntentError::NonceLaneReserved { .. }) => (send_conflict(e), false),
+        e @ (IntentError::Broadcast(_) | IntentError::Storage(_) | IntentError::Superseded(_)) => {
+            (send_failed(&e), true)
+        }
+        e => (send_failed(&e), false),
+    })
+}
+
+async fn refund(state: &WalletServer, delegation: &DelegationRecord, network: &str, value: U256, reason: &str) {
+    if let Err(e) = state.storage.release_delegation_budget(delegation, network, value, reason).await {
+        warn!("delegation {}: refund of {} wei on {} failed: {}", delegation.id, value, network, e);
+    }
+}
@@ -0,0 +1,42 @@
+//! `GET /api/errors`：错误码目录（code、HTTP 状态、是否可重试、说明、i18n 消息键）
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::api::error_catalog::{self, CatalogEntry};
+use crate::api::middleware::authenticate;
+use crate::api::middleware::wallet_scope::extract_wallet_caller;
+use crate::api::server::WalletServer;
+use crate::api::types::ApiError;
+
+#[derive(Debug, Serialize)]
+pub struct ErrorCatalogResponse {
+    pub errors: Vec<CatalogEntry>,
+}
+
+/// `GET /api/errors`（API key、会话或wallet token 均可）
+pub async fn list_error_codes(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+) -> Result<Json<ErrorCatalogResponse>, ApiError> {
+    if authenticate(&headers, &state.api_key).await.is_err() {
+        extract_wallet_caller(&headers, &state).await?;
+    }
+    Ok(Json(ErrorCatalogResponse { errors: error_catalog::catalog() }))
+}
+
+/// `GET /api/test/errors/:code`：按目录声明的状态返回该错误码（仅测试构建）
+///
+/// 一致性测试用它遍历那些真实 handler 难以触发的错误码。
+#[cfg(any(test, feature = "test-env"))]
+pub async fn inject_error(axum::extract::Path(code): axum::extract::Path<String>) -> ApiError {
+    match error_catalog::ApiErrorCode::from_code(&code) {
+        Some(code) => ApiError::generic(code),
+        None => ApiError::new(error_catalog::ApiErrorCode::NotFound, format!("Unknown error code: {}", code)),
+    }
+}
@@ -0,0 +1,102 @@
+//! 事件流（events journal）读取接口（API key）
+//!
+//! 下游系统（记账、风控）按 `seq` 顺序增量拉取：处理完一批后以响应中的
+//! `next_cursor` 作为下一次的 `cursor`，语义为 at-least-once。`wait` 开启
+//! 长轮询：没有新事件时挂起请求直至有事件提交或超时。
+//!
+//! 旧参数 `after_seq` 在一个弃用周期内保留，仍返回 `events`/`next_seq` 形式的响应。
+
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::error;
+
+use super::admin::unauthorized;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::pagination::{PageCursor, PageDirection};
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::server_config::REQUEST_TIMEOUT;
+use crate::api::types::*;
+use crate::api::validators::{ParamError, ValidQuery};
+
+/// 单次最多返回条数
+pub const MAX_EVENTS_PAGE: i64 = 1000;
+const DEFAULT_EVENTS_PAGE: i64 = 100;
+
+/// journal 按 `seq` 升序分页
+#[derive(Debug, Clone, Copy)]
+pub struct EventPages;
+
+impl PageScope for EventPages {
+    const SCOPE: &'static str = "events";
+    const DIRECTION: PageDirection = PageDirection::Asc;
+    const DEFAULT_LIMIT: usize = DEFAULT_EVENTS_PAGE as usize;
+    const MAX_LIMIT: usize = MAX_EVENTS_PAGE as usize;
+}
+/// 长轮询最长等待（秒）；须小于全局请求超时
+pub const MAX_EVENTS_WAIT_SECS: u64 = REQUEST_TIMEOUT.as_secs() - 5;
+/// 长轮询期间未收到通知时的重查间隔
+const JOURNAL_RECHECK: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct EventsQuery {
+    /// 已废弃：改用 `cursor`
+    pub after_seq: Option<i64>,
+    /// 长轮询等待秒数，0 表示立即返回
+    pub wait: Option<u64>,
+}
+
+fn db_error(e: anyhow::Error) -> ApiError {
+    error!("events journal query failed: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to read events")
+}
+
+/// `GET /api/events?cursor=...&limit=500&wait=20`
+pub async fn list_events(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Query(query): Query<EventsQuery>,
+    ValidQuery(request): ValidQuery<PageRequest<EventPages>>,
+) -> Result<Response, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let legacy = request.cursor.is_none() && query.after_seq.is_some();
+    let after_seq = match &request.cursor {
+        Some(cursor) => cursor.as_int().ok_or(ParamError::Cursor)?,
+        None => query.after_seq.unwrap_or(0),
+    }
+    .max(0);
+    let limit = request.limit as i64;
+    let wait = Duration::from_secs(query.wait.unwrap_or(0).min(MAX_EVENTS_WAIT_SECS));
+
+    // subscribe before reading so a commit between the read and the wait is not missed
+    let mut tip = state.storage.subscribe_journal();
+    let mut events = state.storage.journal_events(after_seq, limit).await.map_err(db_error)?;
+    let deadline = tokio::time::Instant::now() + wait;
+    while events.is_empty() && tokio::time::Instant::now() < deadline {
+        // 本进程内的提交会立即唤醒；其它进程写入的事件靠定期重查发现
+        let recheck = (tokio::time::Instant::now() + JOURNAL_RECHECK).min(deadline);
+        if let Ok(Err(_)) = tokio::time::timeout_at(recheck, tip.changed()).await {
+            // storage handle is gone; nothing more will be signalled
+            tokio::time::sleep_until(recheck).await;
+        }
+        events = state.storage.journal_events(after_seq, limit).await.map_err(db_error)?;
+    }
+
+    let next_seq = events.last().map(|e| e.seq).unwrap_or(after_seq);
+    if legacy {
+        return Ok(Json(EventsResponse { events, next_seq }).into_response());
+    }
+    // journal 没有尽头：即使本页为空也返回游标，供下次从同一位置继续
+    let next = PageCursor::int(PageDirection::Asc, next_seq);
+    Ok(Json(request.page(events, Some(next), None).map_err(db_error)?).into_response())
+}
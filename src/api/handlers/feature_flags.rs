@@ -0,0 +1,99 @@
+//! 运行时功能开关 handlers（API key）
+//!
+//! 开关名由 [`crate::feature_flags::Flag`] 在代码中声明，未声明的名字一律 400。
+//! 每次修改在同一事务中写规则、推进 `feature_flags` cache epoch 并追加
+//! `feature_flag.changed` 事件，之后再记审计日志；其他实例在 epoch 检查间隔内生效。
+
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::admin::unauthorized;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::WalletNameParam;
+use crate::feature_flags::{Flag, FlagError, FlagState};
+
+/// 全局规则的审计记录挂在这个 wallet_id 下
+const GLOBAL_AUDIT_ID: &str = "system";
+
+fn flag_error(e: FlagError) -> ApiError {
+    let code = match &e {
+        FlagError::Unknown(_) => ApiErrorCode::UnknownFeatureFlag,
+        FlagError::InvalidRule(_) => ApiErrorCode::InvalidFlagRule,
+        FlagError::Storage(inner) => {
+            error!("feature flag storage error: {}", inner);
+            note_storage_error(inner);
+            return ApiError::new(ApiErrorCode::DbError, "Failed to access feature flags");
+        }
+    };
+    ApiError::new(code, e.to_string())
+}
+
+fn checked_wallet_id(wallet_id: Option<&str>) -> Result<Option<&str>, ApiError> {
+    if let Some(wallet_id) = wallet_id {
+        WalletNameParam::try_from(wallet_id)?;
+    }
+    Ok(wallet_id)
+}
+
+/// `GET /api/admin/flags?wallet_id=`：各开关的默认值、规则与当前判定
+pub async fn list_flags(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Query(query): Query<FlagsQuery>,
+) -> Result<Json<FlagsResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let wallet_id = checked_wallet_id(query.wallet_id.as_deref())?;
+    let flags = state.flags.list(wallet_id).await.map_err(flag_error)?;
+    Ok(Json(FlagsResponse { wallet_id: query.wallet_id.clone(), flags }))
+}
+
+/// `PUT /api/admin/flags`：设置或删除一条规则，返回该开关对同一范围的新判定
+///
+/// wallet规则优先于全局规则；`rollout_percent` 只能用于全局规则。
+pub async fn put_flag(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(req): Json<FlagUpdateRequest>,
+) -> Result<Json<FlagState>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let flag: Flag = req.flag.parse().map_err(flag_error)?;
+    let wallet_id = checked_wallet_id(req.wallet_id.as_deref())?;
+    let updated_by = req.updated_by.as_deref().map(str::trim).filter(|u| !u.is_empty()).unwrap_or("admin");
+
+    let (action, details) = match req.enabled {
+        Some(enabled) => {
+            state.flags.set(flag, wallet_id, enabled, req.rollout_percent, updated_by).await.map_err(flag_error)?;
+            let details = serde_json::json!({
+                "flag": flag,
+                "wallet_id": wallet_id,
+                "enabled": enabled,
+                "rollout_percent": req.rollout_percent,
+                "updated_by": updated_by,
+            });
+            ("feature_flag.updated", details)
+        }
+        None => {
+            state.flags.clear(flag, wallet_id, updated_by).await.map_err(flag_error)?;
+            let details = serde_json::json!({ "flag": flag, "wallet_id": wallet_id, "updated_by": updated_by });
+            ("feature_flag.cleared", details)
+        }
+    };
+    info!("feature flag {} changed by {}: {}", flag, updated_by, details);
+    let audit_id = wallet_id.unwrap_or(GLOBAL_AUDIT_ID);
+    if let Err(e) = state.storage.log_action(audit_id, action, &details.to_string(), None, None).await {
+        error!("failed to audit feature flag change of {}: {}", flag, e);
+    }
+
+    let states = state.flags.list(wallet_id).await.map_err(flag_error)?;
+    let updated = states.into_iter().find(|s| s.flag == flag).expect("every declared flag is listed");
+    Ok(Json(updated))
+}
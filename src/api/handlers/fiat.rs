@@ -0,0 +1,76 @@
+//! 法币估值：余额与历史响应中的可选 `fiat_value`
+//!
+//! 只在 `pricing.enabled` 时生效。价格源报错、超时或没有该币种报价时
+//! 省略法币字段，余额与历史本身照常返回。
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::api::server::WalletServer;
+use crate::api::user_preferences;
+use crate::pricing::{fiat_value_of, PriceFeed};
+
+/// 单次价格查询的上限：缓存未命中且上游慢时不拖住响应
+const PRICE_LOOKUP_TIMEOUT: Duration = Duration::from_secs(2);
+/// 历史条目之前多久内的价格快照可用于 `fiat_value_at_time`
+const PRICE_SNAPSHOT_MAX_AGE_HOURS: i64 = 24;
+
+/// 一次请求的估值上下文
+pub(crate) struct Pricing<'a> {
+    state: &'a WalletServer,
+    feed: Arc<dyn PriceFeed>,
+    /// 小写货币代码
+    pub currency: String,
+}
+
+/// 定价关闭时 `None`；计价货币取user偏好，未登录、无偏好或查询失败时用配置默认值
+pub(crate) async fn pricing_for<'a>(state: &'a WalletServer, user_id: Option<&str>) -> Option<Pricing<'a>> {
+    let feed = state.price_feed.clone()?;
+    let mut currency = None;
+    if let Some(user_id) = user_id {
+        match user_preferences::preferred_currency(state.user_db.pool(), user_id).await {
+            Ok(preferred) => currency = preferred,
+            Err(e) => warn!("preferred currency lookup for {} failed: {}", user_id, e),
+        }
+    }
+    let currency = currency.unwrap_or_else(|| state.config.pricing.default_currency.clone()).to_ascii_lowercase();
+    Some(Pricing { state, feed, currency })
+}
+
+impl Pricing<'_> {
+    /// `symbol` 的当前价格
+    pub(crate) async fn price(&self, symbol: &str) -> Option<Decimal> {
+        match tokio::time::timeout(PRICE_LOOKUP_TIMEOUT, self.feed.get_price(symbol, &self.currency)).await {
+            Ok(Ok(price)) => Some(price.price),
+            Ok(Err(e)) => {
+                warn!("price of {} in {} unavailable: {}", symbol, self.currency, e);
+                None
+            }
+            Err(_) => {
+                warn!("price of {} in {} timed out", symbol, self.currency);
+                None
+            }
+        }
+    }
+
+    /// 余额快照任务在 `at` 之前记录的价格
+    pub(crate) async fn price_at(&self, symbol: &str, at: DateTime<Utc>) -> Option<Decimal> {
+        let max_age = chrono::Duration::hours(PRICE_SNAPSHOT_MAX_AGE_HOURS);
+        match self.state.storage.price_snapshot_at(symbol, &self.currency, at, max_age).await {
+            Ok(price) => price.and_then(|p| Decimal::from_str(&p).ok()),
+            Err(e) => {
+                warn!("price snapshot lookup for {} failed: {}", symbol, e);
+                None
+            }
+        }
+    }
+}
+
+/// 整单位十进制金额（如 `"1.5"`）按 `price` 折算的法币价值
+pub(crate) fn value_of(amount: &str, price: Option<Decimal>) -> Option<String> {
+    fiat_value_of(amount, price?).map(|v| v.to_string())
+}
@@ -0,0 +1,210 @@
+//! wallet资金预检相关handlers
+//!
+//! 新建wallet余额为 0，直接发送会得到令人困惑的下游error。
+//! 这里按network返回address、当前balance、建议gas price以及一次标准转账所需的最小金额
+//! （非 EVM network返回 `UNSUPPORTED_NETWORK`），
+//! 方便前端提示“请至少充值 X”。
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use ethers::utils::format_ether;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, warn};
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::{extract_user_id_from_token, verify_wallet_ownership};
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{validate_and_normalize_network, validate_wallet_name};
+use crate::blockchain::gas_oracle::{min_transfer_cost, GasOracle};
+
+/// 每个network RPC 调用的超时时间；超时的字段降级为 null
+pub const PREFLIGHT_RPC_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// `POST /api/wallets?preflight=eth,polygon`
+#[derive(Debug, Default, Deserialize)]
+pub struct PreflightQuery {
+    pub preflight: Option<String>,
+}
+
+/// `GET /api/wallets/:name/funding_requirements?network=eth`
+#[derive(Debug, Deserialize)]
+pub struct FundingQuery {
+    pub network: String,
+}
+
+pub(crate) fn native_symbol(network: &str) -> &'static str {
+    match network {
+        "eth" | "sepolia" => "ETH",
+        "polygon" => "MATIC",
+        "bsc" => "BNB",
+        "btc" => "BTC",
+        _ => "UNKNOWN",
+    }
+}
+
+fn is_evm_network(network: &str) -> bool {
+    matches!(network, "eth" | "sepolia" | "polygon" | "bsc")
+}
+
+/// 解析 `preflight` 参数（逗号分隔），规范化并去重
+pub fn parse_preflight_networks(
+    raw: &str,
+) -> Result<Vec<String>, ApiError> {
+    let mut networks: Vec<String> = Vec::new();
+    for part in raw.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        let normalized = validate_and_normalize_network(part)?;
+        if !networks.contains(&normalized) {
+            networks.push(normalized);
+        }
+    }
+    Ok(networks)
+}
+
+/// 并发执行所有network的预检；单个network失败不影响其他network
+pub async fn run_preflight(
+    oracle: &Arc<dyn GasOracle>,
+    address: &str,
+    networks: &[String],
+) -> Vec<NetworkPreflight> {
+    futures::future::join_all(
+        networks.iter().map(|network| preflight_network(oracle.as_ref(), address, network)),
+    )
+    .await
+}
+
+async fn preflight_network(oracle: &dyn GasOracle, address: &str, network: &str) -> NetworkPreflight {
+    let mut result = NetworkPreflight {
+        network: network.to_string(),
+        address: None,
+        symbol: Some(native_symbol(network).to_string()),
+        balance: None,
+        gas_price_wei: None,
+        min_transfer_amount: None,
+        error: None,
+    };
+
+    // 非托管模式下 EVM network共用同一个address；BTC address无法由 EVM address推导
+    if !is_evm_network(network) {
+        result.error = Some(ApiErrorCode::UnsupportedNetwork.as_str().to_string());
+        return result;
+    }
+    result.address = Some(address.to_string());
+
+    if !oracle.supports(network) {
+        warn!("preflight: no gas oracle provider for network {}", network);
+        return result;
+    }
+
+    let (balance, gas_price) = tokio::join!(
+        tokio::time::timeout(PREFLIGHT_RPC_TIMEOUT, oracle.native_balance(network, address)),
+        tokio::time::timeout(PREFLIGHT_RPC_TIMEOUT, oracle.gas_price(network)),
+    );
+
+    match balance {
+        Ok(Ok(wei)) => result.balance = Some(format_ether(wei)),
+        Ok(Err(e)) => warn!("preflight: balance query failed on {}: {}", network, e),
+        Err(_) => warn!("preflight: balance query timed out on {}", network),
+    }
+    match gas_price {
+        Ok(Ok(price)) => {
+            result.gas_price_wei = Some(price.to_string());
+            result.min_transfer_amount = Some(format_ether(min_transfer_cost(price)));
+        }
+        Ok(Err(e)) => warn!("preflight: gas price query failed on {}: {}", network, e),
+        Err(_) => warn!("preflight: gas price query timed out on {}", network),
+    }
+
+    result
+}
+
+pub async fn funding_requirements(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Query(query): Query<FundingQuery>,
+) -> Result<Json<NetworkPreflight>, ApiError> {
+    let user_id = extract_user_id_from_token(&headers, &state).await?;
+    verify_wallet_ownership(&user_id, &name, &state).await?;
+    validate_wallet_name(&name)?;
+
+    let network = validate_and_normalize_network(&query.network)?;
+    if !is_evm_network(&network) {
+        return Err(ApiError::new(
+            ApiErrorCode::UnsupportedNetwork,
+            format!("Funding requirements are not available on {}", network),
+        ));
+    }
+
+    let wallets = state.user_db.get_user_wallets_with_address(&user_id)
+        .await
+        .map_err(|e| {
+            error!("fetchuserwalletfailed: user_id={}, error={}", user_id, e);
+            ApiError::new(ApiErrorCode::DbError, "queryuserwalletfailed")
+        })?;
+
+    let address = wallets
+        .into_iter()
+        .find(|w| w.name == name)
+        .and_then(|w| w.address)
+        .ok_or_else(|| {
+            ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found")
+        })?;
+
+    Ok(Json(preflight_network(state.gas_oracle.as_ref(), &address, &network).await))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::gas_oracle::ProviderGasOracle;
+    use ethers::providers::{MockProvider, Provider};
+    use ethers::types::U256;
+
+    const ADDR: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+
+    #[test]
+    fn test_parse_preflight_networks_normalizes_and_dedupes() {
+        let nets = parse_preflight_networks("ethereum, eth,polygon,,").unwrap();
+        assert_eq!(nets, vec!["eth".to_string(), "polygon".to_string()]);
+        assert!(parse_preflight_networks("eth,dogecoin").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_preflight_populates_estimates() {
+        let (provider, mock) = Provider::mocked();
+        // MockProvider 按 LIFO 返回：gas price 与 balance 各一次
+        mock.push(U256::from(20_000_000_000u64)).unwrap();
+        mock.push(U256::zero()).unwrap();
+        let oracle: Arc<dyn GasOracle> =
+            Arc::new(ProviderGasOracle::<MockProvider>::new().with_provider("eth", provider));
+
+        let out = run_preflight(&oracle, ADDR, &["eth".to_string()]).await;
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].address.as_deref(), Some(ADDR));
+        assert!(out[0].gas_price_wei.is_some());
+        assert!(out[0].min_transfer_amount.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_preflight_rpc_failure_degrades_to_null() {
+        // 没有推送任何响应：MockProvider 返回error
+        let (provider, _mock) = Provider::mocked();
+        let oracle: Arc<dyn GasOracle> =
+            Arc::new(ProviderGasOracle::<MockProvider>::new().with_provider("eth", provider));
+
+        let out = run_preflight(&oracle, ADDR, &["eth".to_string(), "btc".to_string()]).await;
+        assert_eq!(out[0].address.as_deref(), Some(ADDR));
+        assert!(out[0].balance.is_none());
+        assert!(out[0].gas_price_wei.is_none());
+        assert!(out[0].error.is_none());
+        assert!(out[1].address.is_none());
+        assert_eq!(out[1].symbol.as_deref(), Some("BTC"));
+        assert_eq!(out[1].error.as_deref(), Some("UNSUPPORTED_NETWORK"));
+    }
+}
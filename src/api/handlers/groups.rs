@@ -0,0 +1,526 @@
+//! wallet组 handlers
+//!
+//! 组由一个user拥有，成员是该user关联的wallet。owner（会话 token）只能看到
+//! 和管理自己的组；admin（API key）可以管理所有组，创建时需指定 `owner_user_id`。
+//! 组级操作（balance、历史、清空）都只作用于成员wallet。
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use ethers::signers::Signer;
+use ethers::types::U256;
+use ethers::utils::{format_ether, parse_ether};
+use futures::stream::{self, StreamExt};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use super::admin::unauthorized;
+use super::fiat::pricing_for;
+use super::funding::PREFLIGHT_RPC_TIMEOUT;
+use super::multi_assets::{add_fiat_values, asset_balances, parse_symbols, MultiAssetsQuery};
+use super::transaction::{send_signed_by_server, Requester, ServerSend};
+use super::wallet_networks::check_network_allowed;
+use super::wallet_tokens::ADMIN_ISSUER;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::extract_user::extract_token;
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::user_db::WalletInfo;
+use crate::api::validators::{Amount, ParamError, ValidJson, ValidPath, ValidQuery, WalletNameParam};
+use crate::blockchain::gas_oracle::max_transfer_cost;
+use crate::intents::DecisionContext;
+use crate::security::error_sanitizer::sanitize_error_message;
+use crate::storage::WalletGroupRecord;
+
+/// 组级 fan-out 的并发上限
+const GROUP_FANOUT_CONCURRENCY: usize = 8;
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("wallet group storage error: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access wallet groups")
+}
+
+fn user_db_error(e: anyhow::Error) -> ApiError {
+    error!("wallet group member lookup failed: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to query user wallets")
+}
+
+/// 会话user返回其 User ID；否则按 API key 认证，admin 返回 `None`
+async fn group_caller(headers: &HeaderMap, state: &Arc<WalletServer>) -> Result<Option<String>, ApiError> {
+    if let Some(token) = extract_token(headers) {
+        if let Ok(user_id) = state.session_store.validate_token(&token).await {
+            return Ok(Some(user_id));
+        }
+    }
+    authenticate(headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    Ok(None)
+}
+
+/// 组记录；会话user不是 owner 时 403，不暴露成员
+pub(crate) async fn load_group(
+    state: &WalletServer,
+    name: &str,
+    caller: Option<&str>,
+) -> Result<WalletGroupRecord, ApiError> {
+    let group = state
+        .storage
+        .wallet_group(name)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| ApiError::new(ApiErrorCode::GroupNotFound, "Wallet group not found"))?;
+    if caller.is_some_and(|user_id| user_id != group.owner_user_id) {
+        return Err(ApiError::new(
+            ApiErrorCode::GroupAccessDenied,
+            "Forbidden: You don't have permission to access this group",
+        ));
+    }
+    Ok(group)
+}
+
+async fn group_response(state: &WalletServer, group: WalletGroupRecord) -> Result<WalletGroupResponse, ApiError> {
+    let members = state.storage.wallet_group_members(&group.name).await.map_err(storage_error)?;
+    Ok(WalletGroupResponse { group, members })
+}
+
+/// 成员wallet及其在 owner 名下的关联信息（按wallet名排序）
+pub(crate) async fn member_wallets(
+    state: &WalletServer,
+    group: &WalletGroupRecord,
+) -> Result<Vec<(String, Option<WalletInfo>)>, ApiError> {
+    let members = state.storage.wallet_group_members(&group.name).await.map_err(storage_error)?;
+    let mut linked = state.user_db.get_user_wallets_with_address(&group.owner_user_id).await.map_err(user_db_error)?;
+    Ok(members
+        .into_iter()
+        .map(|m| {
+            let info = linked.iter().position(|w| w.name == m.wallet_name).map(|i| linked.swap_remove(i));
+            (m.wallet_name, info)
+        })
+        .collect())
+}
+
+fn parse_group_path(group: &str, wallet: &str) -> Result<(WalletNameParam, WalletNameParam), ParamError> {
+    Ok((WalletNameParam::try_from(group)?, WalletNameParam::try_from(wallet)?))
+}
+
+async fn audit(state: &WalletServer, wallet_name: &str, action: &str, details: serde_json::Value) {
+    if let Err(e) = state.storage.log_action(wallet_name, action, &details.to_string(), None, None).await {
+        error!("failed to audit {} on {}: {}", action, wallet_name, e);
+    }
+}
+
+/// `POST /api/groups`
+pub async fn create_group(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidJson(payload): ValidJson<CreateWalletGroup>,
+) -> Result<Json<WalletGroupResponse>, ApiError> {
+    let caller = group_caller(&headers, &state).await?;
+    let (owner, created_by) = match caller {
+        Some(user_id) => (user_id.clone(), user_id),
+        None => (payload.owner_user_id.ok_or(ParamError::Missing("owner_user_id"))?, ADMIN_ISSUER.to_string()),
+    };
+    let name = payload.name.as_str();
+    if !state.storage.create_wallet_group(name, &owner, &created_by).await.map_err(storage_error)? {
+        return Err(ApiError::new(ApiErrorCode::GroupExists, "Wallet group already exists"));
+    }
+    info!("wallet group {} created for {} by {}", name, owner, created_by);
+    let group = load_group(&state, name, None).await?;
+    group_response(&state, group).await.map(Json)
+}
+
+/// `GET /api/groups`：会话user只列出自己的组
+pub async fn list_groups(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+) -> Result<Json<WalletGroupListResponse>, ApiError> {
+    let caller = group_caller(&headers, &state).await?;
+    let groups = state.storage.list_wallet_groups(caller.as_deref()).await.map_err(storage_error)?;
+    Ok(Json(WalletGroupListResponse { groups }))
+}
+
+/// `GET /api/groups/:name`
+pub async fn get_group(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<WalletGroupResponse>, ApiError> {
+    let caller = group_caller(&headers, &state).await?;
+    let group = load_group(&state, name.as_str(), caller.as_deref()).await?;
+    group_response(&state, group).await.map(Json)
+}
+
+/// `DELETE /api/groups/:name`：只删除组与成员关系，wallet不受影响
+pub async fn delete_group(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let caller = group_caller(&headers, &state).await?;
+    let group = load_group(&state, name.as_str(), caller.as_deref()).await?;
+    state.storage.delete_wallet_group(&group.name).await.map_err(storage_error)?;
+    info!("wallet group {} deleted by {}", group.name, caller.as_deref().unwrap_or(ADMIN_ISSUER));
+    Ok(Json(serde_json::json!({ "success": true, "group": group.name })))
+}
+
+/// `PUT /api/groups/:name/members/:wallet`
+///
+/// 成员必须是组 owner 关联的wallet；重复添加幂等。
+pub async fn add_group_member(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((name, wallet)): Path<(String, String)>,
+) -> Result<Json<WalletGroupResponse>, ApiError> {
+    let (name, wallet) = parse_group_path(&name, &wallet)?;
+    let caller = group_caller(&headers, &state).await?;
+    let group = load_group(&state, name.as_str(), caller.as_deref()).await?;
+    let wallet = wallet.as_str();
+
+    let linked = state.user_db.find_user_wallet(&group.owner_user_id, wallet).await.map_err(user_db_error)?;
+    if linked.is_none() {
+        return Err(ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found"));
+    }
+    let added_by = caller.as_deref().unwrap_or(ADMIN_ISSUER);
+    if state.storage.add_wallet_group_member(&group.name, wallet, added_by).await.map_err(storage_error)? {
+        audit(&state, wallet, "wallet_group.member_added", serde_json::json!({ "group": group.name, "by": added_by }))
+            .await;
+    }
+    group_response(&state, group).await.map(Json)
+}
+
+/// `DELETE /api/groups/:name/members/:wallet`
+pub async fn remove_group_member(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((name, wallet)): Path<(String, String)>,
+) -> Result<Json<WalletGroupResponse>, ApiError> {
+    let (name, wallet) = parse_group_path(&name, &wallet)?;
+    let caller = group_caller(&headers, &state).await?;
+    let group = load_group(&state, name.as_str(), caller.as_deref()).await?;
+    let wallet = wallet.as_str();
+
+    if !state.storage.remove_wallet_group_member(&group.name, wallet).await.map_err(storage_error)? {
+        return Err(ApiError::new(ApiErrorCode::MemberNotFound, "Wallet is not a member of this group"));
+    }
+    let by = caller.as_deref().unwrap_or(ADMIN_ISSUER);
+    audit(&state, wallet, "wallet_group.member_removed", serde_json::json!({ "group": group.name, "by": by })).await;
+    group_response(&state, group).await.map(Json)
+}
+
+/// `GET /api/groups/:name/balances?symbols=ETH,USDT`
+///
+/// 与 `/api/wallets/:name/assets` 相同的多资产query，按成员并发执行。
+pub async fn group_balances(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    Query(query): Query<MultiAssetsQuery>,
+) -> Result<Json<GroupBalancesResponse>, ApiError> {
+    let caller = group_caller(&headers, &state).await?;
+    let group = load_group(&state, name.as_str(), caller.as_deref()).await?;
+    let symbols = parse_symbols(query.symbols.as_deref());
+    let pricing = pricing_for(&state, Some(&group.owner_user_id)).await;
+
+    let members = member_wallets(&state, &group).await?;
+    let wallets: Vec<GroupWalletBalances> = stream::iter(members)
+        .map(|(wallet, info)| {
+            let (symbols, pricing) = (&symbols, pricing.as_ref());
+            async move {
+                let Some(address) = info.and_then(|w| w.address) else {
+                    return GroupWalletBalances {
+                        wallet,
+                        balances: HashMap::new(),
+                        error: Some("Wallet address not found".to_string()),
+                    };
+                };
+                let mut balances = asset_balances(&address, symbols).await;
+                if let Some(pricing) = pricing {
+                    add_fiat_values(pricing, &mut balances).await;
+                }
+                GroupWalletBalances { wallet, balances, error: None }
+            }
+        })
+        .buffered(GROUP_FANOUT_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut totals: BTreeMap<String, Decimal> = BTreeMap::new();
+    for asset in wallets.iter().flat_map(|w| w.balances.values()) {
+        if let Ok(amount) = Decimal::from_str(&asset.balance) {
+            *totals.entry(asset.symbol.clone()).or_default() += amount;
+        }
+    }
+
+    Ok(Json(GroupBalancesResponse {
+        group: group.name,
+        wallets,
+        totals: totals.into_iter().map(|(symbol, total)| (symbol, total.normalize().to_string())).collect(),
+        fiat_currency: pricing.map(|p| p.currency),
+    }))
+}
+
+/// `GET /api/groups/:name/history?cursor=&limit=`
+///
+/// 成员wallet的本地transaction记录按时间倒序合并，keyset 分页。
+pub async fn group_history(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(params): ValidQuery<GroupHistoryParams>,
+) -> Result<Json<GroupHistoryResponse>, ApiError> {
+    let caller = group_caller(&headers, &state).await?;
+    let group = load_group(&state, name.as_str(), caller.as_deref()).await?;
+    // records carry `wallets.id`; the history shows member names
+    let mut members = HashMap::new();
+    for member in state.storage.wallet_group_members(&group.name).await.map_err(storage_error)? {
+        if let Some(id) = state.storage.wallet_id(&member.wallet_name).await.map_err(storage_error)? {
+            members.insert(id, member.wallet_name);
+        }
+    }
+    let wallet_ids: Vec<String> = members.keys().cloned().collect();
+
+    let (records, next) = state
+        .storage
+        .wallet_set_transactions(&wallet_ids, params.cursor.as_ref(), params.limit)
+        .await
+        .map_err(storage_error)?;
+    let blockchain = &state.config.blockchain;
+    let transactions = records
+        .into_iter()
+        .map(|r| GroupHistoryEntry {
+            links: ExplorerLinks::render(blockchain, &r.network, &r.tx_hash, Some(&r.from_address), Some(&r.to_address)),
+            wallet: members.get(&r.wallet_id).cloned().unwrap_or_else(|| r.wallet_id.clone()),
+            hash: r.tx_hash,
+            network: r.network,
+            status: r.status,
+            from: r.from_address,
+            to: r.to_address,
+            amount: r.amount,
+            fee: r.fee,
+            created_at: r.created_at,
+        })
+        .collect();
+
+    Ok(Json(GroupHistoryResponse { group: group.name, transactions, next_cursor: next.map(|c| c.encode()) }))
+}
+
+/// 一个成员的清空计划；`Err` 为跳过原因
+fn plan_sweep(wallet: &str, info: Option<WalletInfo>, to: &str) -> Result<String, Box<GroupSweepResult>> {
+    let skipped = |reason: &str, code: &str| {
+        Box::new(GroupSweepResult {
+            wallet: wallet.to_string(),
+            status: "skipped".to_string(),
+            reason: Some(reason.to_string()),
+            code: Some(code.to_string()),
+            ..Default::default()
+        })
+    };
+    let address = info.and_then(|w| w.address).ok_or_else(|| skipped("Wallet address not found", "WALLET_ADDRESS_MISSING"))?;
+    if address.eq_ignore_ascii_case(to) {
+        return Err(skipped("Wallet is the sweep destination", "SWEEP_DESTINATION"));
+    }
+    Ok(address)
+}
+
+/// `POST /api/groups/:name/sweep`
+///
+/// 对每个成员转出 balance - 一次标准转账的手续费，手续费按发送路径的 EIP-1559 上限预留。
+/// 默认 dry run，只返回每个wallet的报告；`dry_run: false` 时按成员依次服务端sign发送
+/// （需要该wallet的Password，超过审查阈值的发送挂起待审批）。单个成员失败不影响其他成员。
+pub async fn sweep_group(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<GroupSweep>,
+) -> Result<Json<GroupSweepResponse>, ApiError> {
+    let caller = group_caller(&headers, &state).await?;
+    let group = load_group(&state, name.as_str(), caller.as_deref()).await?;
+    let network = payload.network.as_str();
+    let to = payload.to.as_str();
+
+    let gas_price = match tokio::time::timeout(PREFLIGHT_RPC_TIMEOUT, state.gas_oracle.gas_price(network)).await {
+        Ok(Ok(price)) => price,
+        Ok(Err(e)) => {
+            warn!("group sweep: gas price query failed on {}: {}", network, e);
+            return Err(ApiError::new(ApiErrorCode::GasPriceUnavailable, "Gas price unavailable"));
+        }
+        Err(_) => {
+            return Err(ApiError::new(ApiErrorCode::GasPriceTimeout, "Gas price query timed out"))
+        }
+    };
+    let fee = max_transfer_cost(gas_price);
+
+    // 先并发读取所有成员balance，得到每个wallet的计划
+    let members = member_wallets(&state, &group).await?;
+    let mut results: Vec<GroupSweepResult> = stream::iter(members)
+        .map(|(wallet, info)| {
+            let state = &state;
+            async move {
+                let address = match plan_sweep(&wallet, info, to) {
+                    Ok(address) => address,
+                    Err(skipped) => return *skipped,
+                };
+                let mut result = GroupSweepResult {
+                    wallet,
+                    address: Some(address.clone()),
+                    fee: Some(format_ether(fee)),
+                    ..Default::default()
+                };
+                let balance = match tokio::time::timeout(
+                    PREFLIGHT_RPC_TIMEOUT,
+                    state.gas_oracle.native_balance(network, &address),
+                )
+                .await
+                {
+                    Ok(Ok(balance)) => balance,
+                    Ok(Err(e)) => {
+                        warn!("group sweep: balance query failed for {}: {}", result.wallet, e);
+                        return failed(result, "Balance query failed", "BALANCE_UNAVAILABLE");
+                    }
+                    Err(_) => return failed(result, "Balance query timed out", "BALANCE_UNAVAILABLE"),
+                };
+                result.balance = Some(format_ether(balance));
+                match balance.checked_sub(fee).filter(|amount| !amount.is_zero()) {
+                    Some(amount) => {
+                        result.status = "would_send".to_string();
+                        result.amount = Some(format_ether(amount));
+                    }
+                    None => {
+                        result.status = "skipped".to_string();
+                        result.reason = Some("Balance does not cover the fee".to_string());
+                        result.code = Some("INSUFFICIENT_BALANCE".to_string());
+                    }
+                }
+                result
+            }
+        })
+        .buffered(GROUP_FANOUT_CONCURRENCY)
+        .collect()
+        .await;
+
+    if !payload.dry_run {
+        let principal = caller.as_deref().unwrap_or(ADMIN_ISSUER);
+        // 所有成员同一个收款方：检查一次，未确认的合约收款方使整批失败
+        let refused = match to.parse() {
+            Ok(recipient) => state
+                .recipient_guard
+                .check(state.gas_oracle.as_ref(), network, recipient, payload.acknowledge_contract_recipient)
+                .await
+                .err(),
+            Err(_) => None,
+        };
+        for result in results.iter_mut().filter(|r| r.status == "would_send") {
+            match &refused {
+                Some(e) => *result = failed(std::mem::take(result), &e.to_string(), e.code()),
+                None => execute_sweep(&state, &group.name, principal, result, &payload, network).await,
+            }
+        }
+    }
+
+    let total = results
+        .iter()
+        .filter(|r| matches!(r.status.as_str(), "would_send" | "sent" | "pending_approval"))
+        .filter_map(|r| r.amount.as_deref().and_then(|a| parse_ether(a).ok()))
+        .fold(U256::zero(), |acc, amount| acc.saturating_add(amount));
+
+    Ok(Json(GroupSweepResponse {
+        group: group.name,
+        network: network.to_string(),
+        to: to.to_string(),
+        dry_run: payload.dry_run,
+        gas_price_wei: gas_price.to_string(),
+        total_amount: format_ether(total),
+        results,
+    }))
+}
+
+fn failed(mut result: GroupSweepResult, reason: &str, code: &str) -> GroupSweepResult {
+    result.status = "failed".to_string();
+    result.reason = Some(reason.to_string());
+    result.code = Some(code.to_string());
+    result
+}
+
+/// 发送一个成员的计划金额，结果写回 `result`
+async fn execute_sweep(
+    state: &WalletServer,
+    group: &str,
+    principal: &str,
+    result: &mut GroupSweepResult,
+    payload: &GroupSweep,
+    network: &str,
+) {
+    let wallet = result.wallet.clone();
+    let Some(password) = payload.passwords.get(&wallet) else {
+        result.status = "skipped".to_string();
+        result.reason = Some("No password provided for this wallet".to_string());
+        result.code = Some("MISSING_PARAMETER".to_string());
+        return;
+    };
+    if let Err(e) = check_network_allowed(state, &wallet, network) {
+        *result = failed(std::mem::take(result), e.message(), e.code().as_str());
+        return;
+    }
+    // balance读自关联address；只在sign密钥确实控制该address时发送
+    match state.wallet_manager.ethereum_signer(&wallet, password).await {
+        Ok(signer) => {
+            let signer_address = format!("{:#x}", signer.address());
+            if !result.address.as_deref().is_some_and(|a| a.eq_ignore_ascii_case(&signer_address)) {
+                *result = failed(std::mem::take(result), "Signing key does not control the linked address", "ADDRESS_MISMATCH");
+                return;
+            }
+        }
+        Err(e) => {
+            let reason = sanitize_error_message(&e.to_string());
+            *result = failed(std::mem::take(result), &reason, "TRANSACTION_FAILED");
+            return;
+        }
+    }
+    let amount = match result.amount.as_deref().map(Amount::try_from) {
+        Some(Ok(amount)) => amount,
+        _ => {
+            *result = failed(std::mem::take(result), "Invalid sweep amount", "INVALID_AMOUNT");
+            return;
+        }
+    };
+
+    let requester = Requester { principal, token_id: None };
+    let decision = DecisionContext::new();
+    match send_signed_by_server(state, requester, &wallet, &payload.to, &amount, network, password, false, decision)
+        .await
+    {
+        Ok(ServerSend::Sent(tx_hash)) => {
+            result.status = "sent".to_string();
+            result.tx_hash = Some(tx_hash);
+        }
+        Ok(ServerSend::Held(approval)) => {
+            result.status = "pending_approval".to_string();
+            result.approval_id = Some(approval.id);
+        }
+        Err(e) => *result = failed(std::mem::take(result), e.message(), e.code().as_str()),
+    }
+    audit(
+        state,
+        &wallet,
+        "wallet_group.sweep",
+        serde_json::json!({
+            "group": group,
+            "network": network,
+            "to": payload.to.as_str(),
+            "amount": amount.as_str(),
+            "status": result.status,
+            "tx_hash": result.tx_hash,
+            "approval_id": result.approval_id,
+            "by": principal,
+        }),
+    )
+    .await;
+}
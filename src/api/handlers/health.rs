@@ -1,13 +1,28 @@
 //! 健康check和指标相关handlers
 
+use axum::extract::State;
 use serde_json::json;
+use std::sync::Arc;
+
+use crate::api::server::WalletServer;
 
 /// 健康check
-pub async fn health_check() -> axum::response::Json<serde_json::Value> {
+pub async fn health_check(
+    State(state): State<Arc<WalletServer>>,
+) -> axum::response::Json<serde_json::Value> {
+    let admission = state.admission.snapshot();
+    // 签名槽位全部占满且有请求在排队时报告 degraded
+    let status = if admission.saturated { "degraded" } else { "healthy" };
+    // 多实例部署时据此发现 schema 不一致的实例；读取失败时为 null
+    let schema_version = state.storage.schema_version().await.ok();
     axum::response::Json(json!({
-        "status": "healthy",
+        "status": status,
         "version": env!("CARGO_PKG_VERSION"),
-        "timestamp": chrono::Utc::now().to_rfc3339()
+        "schema_version": schema_version,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "bridge_backend": state.bridge_factory.backend().as_str(),
+        "sandbox": state.sandbox,
+        "admission": admission
     }))
 }
 
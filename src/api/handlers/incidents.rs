@@ -0,0 +1,60 @@
+//! 进程事故查询（API key）
+//!
+//! panic 由 `ops::incidents` 的 panic hook 记录到 `process_incidents` 表，所有实例
+//! 共享；错误分类计数只在内存中，反映的是处理本次请求的实例。
+
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+use super::admin::unauthorized;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::ops::incidents::MAX_TALLY_WINDOW_SECS;
+
+/// 单次最多返回的 panic 条数
+pub const MAX_INCIDENTS: i64 = 500;
+const DEFAULT_INCIDENTS: i64 = 50;
+const DEFAULT_WINDOW_SECS: u64 = 3600;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct IncidentsQuery {
+    /// 1 秒到 24 小时，超出时截断
+    pub window_secs: Option<u64>,
+    pub limit: Option<i64>,
+}
+
+/// `GET /api/admin/incidents?window_secs=3600&limit=50`
+pub async fn list_incidents(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Query(query): Query<IncidentsQuery>,
+) -> Result<Json<IncidentsResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let window_secs = query.window_secs.unwrap_or(DEFAULT_WINDOW_SECS).clamp(1, MAX_TALLY_WINDOW_SECS);
+    let limit = query.limit.unwrap_or(DEFAULT_INCIDENTS).clamp(1, MAX_INCIDENTS);
+    let now = chrono::Utc::now();
+    let since = now - chrono::Duration::seconds(window_secs as i64);
+    let (panics, panic_count) = state.storage.recent_incidents(since, limit).await.map_err(|e| {
+        error!("incident query failed: {}", e);
+        note_storage_error(&e);
+        ApiError::new(ApiErrorCode::DbError, "Failed to load incidents")
+    })?;
+
+    Ok(Json(IncidentsResponse {
+        window_secs,
+        panics,
+        panic_count,
+        error_classes: state.errors.top(now.timestamp(), window_secs),
+    }))
+}
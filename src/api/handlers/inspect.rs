@@ -0,0 +1,192 @@
+//! 任意链上transaction的查询与解码（客服排查用）
+//!
+//! `GET /api/networks/:network/transactions/:hash` 经由配置的network客户端读取
+//! transaction与回执，返回规范化视图：金额按原生币精度换算，已知 selector 的
+//! calldata 与 Transfer/Approval 日志解码，未知 selector 只给出 selector 与长度。
+//!
+//! from/to 是否为托管wallet：admin（API key）可看到wallet名；读权限调用方
+//! （会话 user或带 `read_history` 的wallet token）只得到布尔值。
+
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Json,
+};
+use ethers::types::U64;
+use ethers::utils::{format_ether, to_checksum};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::error;
+
+use super::funding::native_symbol;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::wallet_scope::{extract_wallet_caller, WalletCaller};
+use crate::api::server::WalletServer;
+use crate::api::types::{ApiError, ExplorerLinks};
+use crate::api::validators::{NetworkName, TxHash};
+use crate::blockchain::tx_inspect::{decode_call, decode_log, DecodedCall, DecodedLog};
+use crate::core::errors::WalletError;
+use crate::storage::WalletCapability;
+
+/// from/to 是否属于托管wallet；wallet名只对 admin 返回
+#[derive(Debug, Serialize)]
+pub struct ManagedAddresses {
+    pub from: bool,
+    pub to: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_wallet: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_wallet: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InspectedTransaction {
+    pub network: String,
+    pub hash: String,
+    pub from: String,
+    /// 合约创建时为 `None`
+    pub to: Option<String>,
+    /// 原生币，十进制
+    pub value: String,
+    pub value_wei: String,
+    pub symbol: &'static str,
+    pub nonce: String,
+    /// `pending` | `success` | `failed`
+    pub status: &'static str,
+    pub block_number: Option<u64>,
+    pub confirmations: u64,
+    pub gas_limit: String,
+    pub gas_used: Option<String>,
+    /// wei
+    pub effective_gas_price: Option<String>,
+    /// 原生币，十进制；未上链时为 `None`
+    pub fee: Option<String>,
+    /// 无 calldata（纯转账）时为 `None`
+    pub call: Option<DecodedCall>,
+    pub logs: Vec<DecodedLog>,
+    pub managed: ManagedAddresses,
+    #[serde(flatten)]
+    pub links: ExplorerLinks,
+}
+
+/// API key 为 admin；否则要求会话 token，或带 `read_history` 的wallet token
+pub(crate) async fn is_admin_caller(headers: &HeaderMap, state: &Arc<WalletServer>) -> Result<bool, ApiError> {
+    if authenticate(headers, &state.api_key).await.is_ok() {
+        return Ok(true);
+    }
+    if let WalletCaller::Token(token) = extract_wallet_caller(headers, state).await? {
+        if !token.allows(WalletCapability::ReadHistory) {
+            return Err(ApiError::new(
+                ApiErrorCode::TokenCapabilityDenied,
+                "Forbidden: Token lacks the read_history capability",
+            ));
+        }
+    }
+    Ok(false)
+}
+
+/// 托管wallet名；`address` 为 user_db 中的存储形式（EVM 为小写 hex）
+pub(crate) async fn managed_wallet(state: &WalletServer, address: &str) -> Result<Option<String>, ApiError> {
+    state
+        .user_db
+        .find_wallet_by_address(address)
+        .await
+        .map(|found| found.map(|(_, wallet_name)| wallet_name))
+        .map_err(|e| {
+            error!("managed address lookup failed: {}", e);
+            ApiError::new(ApiErrorCode::DbError, "Failed to query wallets")
+        })
+}
+
+/// `GET /api/networks/:network/transactions/:hash`
+pub async fn inspect_transaction(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((network, hash)): Path<(String, String)>,
+) -> Result<Json<InspectedTransaction>, ApiError> {
+    let is_admin = is_admin_caller(&headers, &state).await?;
+    let network = NetworkName::try_from(network.as_str())?.require_evm()?;
+    let network = network.as_str();
+    let hash = TxHash::try_from(hash.as_str())?;
+    let hash = match hash.as_str().strip_prefix("0x") {
+        Some(_) => hash.as_str().to_string(),
+        None => format!("0x{}", hash.as_str()),
+    };
+
+    let client = state
+        .chain_clients
+        .get(network)
+        .map_err(|e| ApiError::new(ApiErrorCode::NetworkUnavailable, e.to_string()))?;
+    let details = client
+        .get_transaction_details(&hash)
+        .await
+        .map_err(|e| {
+            error!("transaction lookup {} on {} failed: {}", hash, network, e);
+            match e {
+                WalletError::NetworkBusy(_) => ApiError::new(
+                    ApiErrorCode::NetworkBusy,
+                    format!("{} RPC quota exhausted, retry shortly", network),
+                ),
+                _ => {
+                    ApiError::new(ApiErrorCode::NetworkError, "Failed to fetch transaction from the network")
+                }
+            }
+        })?
+        .ok_or_else(|| {
+            ApiError::new(ApiErrorCode::TransactionNotFound, format!("Transaction {} not found on {}", hash, network))
+        })?;
+
+    let tx = &details.transaction;
+    let receipt = details.receipt.as_ref();
+    let block_number = details.block_number();
+    let confirmations = details.confirmations();
+    let status = match receipt.map(|r| r.status) {
+        None => "pending",
+        Some(Some(s)) if s == U64::from(1) => "success",
+        Some(_) => "failed",
+    };
+    let effective_gas_price = receipt.and_then(|r| r.effective_gas_price).or(tx.gas_price);
+    let fee = receipt
+        .and_then(|r| r.gas_used)
+        .zip(effective_gas_price)
+        .map(|(used, price)| format_ether(used * price));
+
+    let from_wallet = managed_wallet(&state, &format!("{:?}", tx.from)).await?;
+    let to_wallet = match tx.to {
+        Some(to) => managed_wallet(&state, &format!("{:?}", to)).await?,
+        None => None,
+    };
+    let managed = ManagedAddresses {
+        from: from_wallet.is_some(),
+        to: to_wallet.is_some(),
+        from_wallet: from_wallet.filter(|_| is_admin),
+        to_wallet: to_wallet.filter(|_| is_admin),
+    };
+
+    let from = to_checksum(&tx.from, None);
+    let to = tx.to.map(|to| to_checksum(&to, None));
+    let links = ExplorerLinks::render(&state.config.blockchain, network, &hash, Some(&from), to.as_deref());
+
+    Ok(Json(InspectedTransaction {
+        network: network.to_string(),
+        hash,
+        from,
+        to,
+        value: format_ether(tx.value),
+        value_wei: tx.value.to_string(),
+        symbol: native_symbol(network),
+        nonce: tx.nonce.to_string(),
+        status,
+        block_number,
+        confirmations,
+        gas_limit: tx.gas.to_string(),
+        gas_used: receipt.and_then(|r| r.gas_used).map(|g| g.to_string()),
+        effective_gas_price: effective_gas_price.map(|p| p.to_string()),
+        fee,
+        call: decode_call(&tx.input),
+        logs: receipt.map(|r| r.logs.iter().filter_map(decode_log).collect()).unwrap_or_default(),
+        managed,
+        links,
+    }))
+}
@@ -0,0 +1,58 @@
+//! sign密钥使用量handlers
+//!
+//! 所有服务端sign路径在sign前调用 [`authorize_signing`]：计数并按
+//! `security.key_rotation` 策略决定告警或拒绝 (`KEY_ROTATION_REQUIRED`)。
+
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Json,
+};
+use std::sync::Arc;
+
+use super::deadman::implicit_checkin;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::request_metrics::note_wallet_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::core::errors::WalletError;
+use crate::security::key_usage::KeyUsageReport;
+
+fn key_usage_error(e: WalletError) -> ApiError {
+    match e {
+        WalletError::KeyRotationRequired(msg) => ApiError::new(ApiErrorCode::KeyRotationRequired, msg),
+        other => {
+            tracing::error!("key usage accounting failed: {}", other);
+            note_wallet_error(&other);
+            ApiError::new(ApiErrorCode::KeyUsageError, "Key usage accounting unavailable")
+        }
+    }
+}
+
+/// sign前调用：记录一次使用并执行轮换策略；放行的sign同时算作dead-man签到
+pub(crate) async fn authorize_signing(
+    state: &WalletServer,
+    wallet_name: &str,
+) -> Result<(), ApiError> {
+    state.key_usage.record_signature(wallet_name).await.map_err(key_usage_error)?;
+    implicit_checkin(state, wallet_name).await;
+    Ok(())
+}
+
+/// `GET /api/wallets/:name/key_usage`
+pub async fn key_usage(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Result<Json<KeyUsageReport>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| {
+        ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
+    })?;
+
+    if name.is_empty() || name.contains(|c: char| !c.is_alphanumeric() && c != '_') {
+        return Err(ApiError::new(ApiErrorCode::InvalidWalletName, "Invalid wallet name"));
+    }
+
+    state.key_usage.usage(&name).await.map(Json).map_err(key_usage_error)
+}
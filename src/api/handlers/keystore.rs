@@ -0,0 +1,291 @@
+//! keystore V3 导入/导出handlers，以及与合作方交换用的签名信封格式；
+//! 整个wallet在实例之间迁移用 `/api/wallets/:name/export` 与 `/api/wallets/import`
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use std::sync::Arc;
+use ethers::core::k256::ecdsa::SigningKey;
+use zeroize::Zeroizing;
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::request_metrics::note_wallet_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{validate_wallet_name, Validate};
+use crate::core::errors::WalletError;
+use crate::core::wallet_info::WalletKeyKind;
+use crate::crypto::keystore_envelope::{EnvelopeError, KeystoreEnvelope};
+use crate::crypto::keystore_v3::KeystoreV3;
+use crate::storage::{WalletNotes, WalletStorageTrait};
+
+/// 导出信封时使用的传输私钥（32字节 hex），未设置则导出接口不可用
+const TRANSPORT_KEY_ENV: &str = "KEYSTORE_TRANSPORT_KEY";
+
+fn unauthorized() -> ApiError {
+    ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
+}
+
+fn keystore_error(e: WalletError) -> ApiError {
+    let (error, code) = match e {
+        WalletError::DecryptionError(msg) => (msg, ApiErrorCode::KeystoreMacMismatch),
+        WalletError::InvalidPassword(msg) => (msg, ApiErrorCode::InvalidPassword),
+        WalletError::ValidationError(msg) if msg.contains("already exists") => {
+            (msg, ApiErrorCode::WalletExists)
+        }
+        WalletError::DeserializationError(msg)
+        | WalletError::ValidationError(msg)
+        | WalletError::KeyDerivationError(msg)
+        | WalletError::InvalidPrivateKey(msg) => (msg, ApiErrorCode::InvalidKeystore),
+        WalletError::SecurityError(msg) => (msg, ApiErrorCode::WeakPassword),
+        WalletError::NotFoundError(msg) => (msg, ApiErrorCode::WalletNotFound),
+        // decrypt_master_key: wrong wallet password
+        WalletError::CryptoError(_) => {
+            ("Invalid wallet password".to_string(), ApiErrorCode::InvalidPassword)
+        }
+        other => {
+            tracing::error!("keystore operation failed: {}", other);
+            note_wallet_error(&other);
+            ("Keystore operation failed".to_string(), ApiErrorCode::KeystoreFailed)
+        }
+    };
+    ApiError::new(code, error)
+}
+
+fn envelope_error(e: EnvelopeError) -> ApiError {
+    match e {
+        EnvelopeError::Wallet(inner) => keystore_error(inner),
+        other => {
+            // 信封完好但不是约定的对方签发
+            let status = if matches!(other, EnvelopeError::SenderMismatch { .. }) {
+                StatusCode::FORBIDDEN
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+            ApiError::from_code(status, other.code(), other.to_string())
+        }
+    }
+}
+
+/// `POST /api/wallets/import_keystore`
+pub async fn import_keystore(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(payload): Json<ImportKeystoreRequest>,
+) -> Result<Json<WalletResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    validate_wallet_name(&payload.name)?;
+
+    // 兼容直接上传文件内容（JSON字符串）和已解析的对象
+    let keystore_json = Zeroizing::new(match &payload.keystore {
+        serde_json::Value::String(raw) => raw.clone(),
+        other => other.to_string(),
+    });
+
+    let address = state
+        .wallet_manager
+        .import_keystore_v3(&payload.name, &keystore_json, &payload.keystore_password, &payload.wallet_password)
+        .await
+        .map_err(keystore_error)?;
+
+    Ok(Json(WalletResponse {
+        id: payload.name.clone(),
+        name: payload.name.clone(),
+        address,
+        quantum_safe: false,
+        wallet_type: Some("imported_key".to_string()),
+        mnemonic: None, // 导入的单私钥没有mnemonic
+        warning: None,
+        preflight: None,
+        networks: None,
+        description: None,
+        metadata: None,
+    }))
+}
+
+/// `POST /api/wallets/:name/export_keystore`
+pub async fn export_keystore(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(payload): Json<ExportKeystoreRequest>,
+) -> Result<Json<KeystoreV3>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    validate_wallet_name(&name)?;
+
+    state
+        .wallet_manager
+        .export_keystore_v3(&name, &payload.wallet_password, &payload.export_password)
+        .await
+        .map(Json)
+        .map_err(keystore_error)
+}
+
+/// `POST /api/wallets/:name/export`
+///
+/// 导出整个wallet（密钥类型、网络、阈值）为带版本号的 keystore，供另一实例导入
+pub async fn export_wallet(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(payload): Json<ExportWalletRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    validate_wallet_name(&name)?;
+
+    let keystore_json = state.wallet_manager.export_wallet(&name, &payload.password).await.map_err(keystore_error)?;
+    serde_json::from_str(&keystore_json)
+        .map(Json)
+        .map_err(|e| keystore_error(WalletError::SerializationError(e.to_string())))
+}
+
+/// `POST /api/wallets/import`
+///
+/// Password错误（MAC 不匹配）返回 401 `INVALID_PASSWORD`；导入成功即写入数据库
+pub async fn import_wallet(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(payload): Json<ImportWalletRequest>,
+) -> Result<Json<WalletResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    validate_wallet_name(&payload.name)?;
+
+    let keystore_json = Zeroizing::new(match &payload.keystore {
+        serde_json::Value::String(raw) => raw.clone(),
+        other => other.to_string(),
+    });
+    let storage: Arc<dyn WalletStorageTrait + Send + Sync> = state.storage.clone();
+    let address = state
+        .wallet_manager
+        .import_wallet(&payload.name, &keystore_json, &payload.password, &storage)
+        .await
+        .map_err(keystore_error)?;
+
+    let wallet = state.wallet_manager.get_wallet_by_name(&payload.name).await.ok().flatten();
+    Ok(Json(WalletResponse {
+        id: payload.name.clone(),
+        name: payload.name.clone(),
+        address,
+        quantum_safe: wallet.as_ref().is_some_and(|w| w.info.quantum_safe),
+        wallet_type: wallet.map(|w| {
+            match w.key_kind {
+                WalletKeyKind::Hd => "standard",
+                WalletKeyKind::ImportedKey => "imported_key",
+            }
+            .to_string()
+        }),
+        mnemonic: None, // keystore 中只有密钥，没有mnemonic
+        warning: None,
+        preflight: None,
+        networks: None,
+        description: None,
+        metadata: None,
+    }))
+}
+
+/// `POST /api/wallets/import_enveloped_keystore`
+///
+/// 验签通过后按 V3 导入，信封 meta 与签名方公钥记录到wallet元数据（`keystore.*`）
+pub async fn import_enveloped_keystore(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(payload): Json<ImportEnvelopedKeystoreRequest>,
+) -> Result<Json<WalletResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    validate_wallet_name(&payload.name)?;
+
+    // 已解析的对象重新序列化不影响验签：签名覆盖的是规范化后的 JSON
+    let envelope_json = match &payload.envelope {
+        serde_json::Value::String(raw) => raw.clone(),
+        other => other.to_string(),
+    };
+
+    let imported = state
+        .wallet_manager
+        .import_enveloped_keystore(
+            &payload.name,
+            &envelope_json,
+            &payload.keystore_password,
+            &payload.wallet_password,
+            &payload.expected_sender,
+        )
+        .await
+        .map_err(envelope_error)?;
+
+    let mut metadata = serde_json::Map::new();
+    metadata.insert("keystore.tool".into(), imported.meta.tool.clone().into());
+    metadata.insert("keystore.origin".into(), imported.meta.origin.clone().into());
+    metadata.insert("keystore.address".into(), imported.meta.address.clone().into());
+    metadata.insert("keystore.sender".into(), imported.sender.clone().into());
+    if let Some(label) = &imported.meta.label {
+        metadata.insert("keystore.label".into(), label.clone().into());
+    }
+    // 对方填写的内容与 PATCH 走同一套校验；不合格时wallet照常导入，只是不记录
+    let notes = match WalletNotesPatch::validate(UpdateWalletNotesRequest { description: None, metadata }) {
+        Ok(patch) => WalletNotes { description: None, metadata: patch.metadata },
+        Err(e) => {
+            tracing::warn!("envelope meta of {} not recorded: {}", payload.name, e);
+            WalletNotes::default()
+        }
+    };
+    if let Err(e) = super::backup::persist_restored(&state, &payload.name, false, Some(&notes)).await {
+        tracing::warn!("failed to persist imported wallet {}: {}", payload.name, e);
+    }
+
+    Ok(Json(WalletResponse {
+        id: payload.name.clone(),
+        name: payload.name.clone(),
+        address: imported.address,
+        quantum_safe: false,
+        wallet_type: Some("imported_key".to_string()),
+        mnemonic: None,
+        warning: None,
+        preflight: None,
+        networks: None,
+        description: None,
+        metadata: (!notes.metadata.is_empty()).then_some(notes.metadata),
+    }))
+}
+
+/// `POST /api/wallets/:name/export_enveloped_keystore`
+///
+/// 用 `KEYSTORE_TRANSPORT_KEY` 签名；对方据 `signature.sender` 核对来源
+pub async fn export_enveloped_keystore(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(payload): Json<ExportEnvelopedKeystoreRequest>,
+) -> Result<Json<KeystoreEnvelope>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    validate_wallet_name(&name)?;
+
+    let transport_key = std::env::var(TRANSPORT_KEY_ENV)
+        .ok()
+        .map(Zeroizing::new)
+        .and_then(|hex_key| {
+            let bytes = Zeroizing::new(hex::decode(hex_key.trim().trim_start_matches("0x")).ok()?);
+            SigningKey::from_slice(&bytes).ok()
+        })
+        .ok_or_else(|| {
+            ApiError::new(
+                ApiErrorCode::TransportKeyUnavailable,
+                format!("{} is not set to a valid secp256k1 key", TRANSPORT_KEY_ENV),
+            )
+        })?;
+
+    state
+        .wallet_manager
+        .export_enveloped_keystore(
+            &name,
+            &payload.wallet_password,
+            &payload.export_password,
+            payload.label.as_deref(),
+            &transport_key,
+        )
+        .await
+        .map(Json)
+        .map_err(envelope_error)
+}
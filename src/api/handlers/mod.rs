@@ -2,26 +2,139 @@
 //! 
 //! 按功能拆分的HTTP请求处理器
 
+pub mod account_abstraction;
 pub mod address;
+pub mod address_book;
+pub mod address_validation;
+pub mod admin;
+pub mod allowances;
+pub mod api_keys;
+pub mod analytics;
+pub mod approvals;
+pub mod attestations;
+pub mod backfill;
 pub mod backup;
 pub mod balance;
+pub mod balance_history;
+pub mod balance_subscriptions;
+pub mod bundles;
+pub mod custodial;
+pub mod db_backups;
+pub mod db_maintenance;
+pub mod deadman;
+pub mod delegations;
+pub mod error_catalog;
+pub mod events;
+pub mod feature_flags;
+pub(crate) mod fiat;
 pub mod bridge;
+#[cfg(feature = "bitcoin")]
+pub mod btc_descriptor;
+pub mod funding;
+pub mod groups;
 pub mod health;
+pub mod incidents;
+pub mod inspect;
+pub mod key_usage;
+pub mod keystore;
 pub mod multisig;
 pub mod multi_assets;
+pub mod networks;
+pub mod nonces;
+pub mod payment_uri;
+pub mod reconciliation;
+pub mod relay;
+pub mod reserve_reports;
+#[cfg(any(test, feature = "test-env"))]
+pub mod sandbox;
+pub mod spending_limits;
 pub mod system_info;
+pub mod timelocks;
+pub mod tokens;
 pub mod transaction;
+pub mod tx_wait;
+pub mod tx_ws;
+pub mod user_erasure;
 pub mod wallet;
+pub mod wallet_networks;
+pub mod wallet_notes;
+pub mod wallet_tokens;
 
 // 重新导出常用handlers
+pub use account_abstraction::{aa_operation, aa_send};
 pub use address::get_wallet_address;
-pub use backup::{backup_wallet, restore_wallet};
+pub use address_book::{export_address_book, import_address_book};
+pub use address_validation::validate_address;
+pub use admin::{
+    admin_summary, admin_transactions, broadcast_raw_transaction, list_audit_logs, list_jobs,
+    rebuild_wallet_profiles, recheck_transactions, reconcile_intents, run_job,
+};
+pub use allowances::{approve_token, get_allowance};
+pub use api_keys::rotate_api_key;
+pub use analytics::fee_analytics;
+pub use approvals::{
+    approve_approval, get_review_threshold, list_approvals, put_review_threshold, reject_approval,
+};
+pub use backfill::{backfill_status, request_backfill};
+pub use backup::{backup_wallet, backup_wallet_shamir, restore_wallet, restore_wallet_shamir};
 pub use balance::get_balance;
-pub use bridge::{bridge_assets, bridge_history, bridge_status};
+pub use balance_history::balance_history;
+pub use balance_subscriptions::{
+    create_balance_subscription, delete_balance_subscription, list_balance_subscriptions,
+    update_balance_subscription,
+};
+pub use bundles::{get_bundle, resume_bundle, submit_bundle};
+pub use custodial::{create_custodial_wallet, custodial_wallet_balance, list_custodial_wallets};
+pub use db_backups::{list_backups, run_backup};
+pub use db_maintenance::{db_checkpoint, db_vacuum_into};
+pub use attestations::{create_attestation, list_attestations, revoke_attestation, verify_attestation};
+pub use deadman::{cancel_deadman_policy, deadman_checkin, get_deadman_policy, put_deadman_policy};
+pub use delegations::{create_delegation, get_delegation, list_delegations, revoke_delegation};
+#[cfg(any(test, feature = "test-env"))]
+pub use error_catalog::inject_error;
+pub use error_catalog::list_error_codes;
+pub use events::list_events;
+pub use bridge::{bridge_assets, bridge_history, bridge_routes, bridge_status};
+#[cfg(feature = "bitcoin")]
+pub use btc_descriptor::export_btc_descriptor;
+pub use feature_flags::{list_flags, put_flag};
+pub use funding::funding_requirements;
+pub use groups::{
+    add_group_member, create_group, delete_group, get_group, group_balances, group_history, list_groups,
+    remove_group_member, sweep_group,
+};
 pub use health::{health_check, metrics};
-pub use multisig::{rotate_signing_key, send_multi_sig_transaction};
+pub use incidents::list_incidents;
+pub use inspect::inspect_transaction;
+pub use key_usage::key_usage;
+pub use keystore::{
+    export_enveloped_keystore, export_keystore, export_wallet, import_enveloped_keystore, import_keystore,
+    import_wallet,
+};
+pub use networks::list_networks;
+pub use nonces::reconcile_nonces;
+pub use payment_uri::{decode_payment_uri, wallet_payment_uri};
+pub use multisig::{
+    create_multisig_proposal, get_multisig_policy, get_multisig_proposal, put_multisig_policy,
+    rotate_signing_key, send_multi_sig_transaction,
+};
+pub use reconciliation::{create_reconciliation, get_reconciliation};
+pub use relay::{list_meta_tx_relays, relay_meta_tx};
+pub use reserve_reports::{create_reserve_report, get_reserve_report, list_reserve_reports, verify_reserve_report};
+#[cfg(any(test, feature = "test-env"))]
+pub use sandbox::sandbox_clone;
+pub use spending_limits::{get_spending_limits, put_spending_limits};
+pub use system_info::version;
+pub use timelocks::{create_timelock, list_timelocks, release_timelock};
+pub use tokens::{clear_token_behavior, list_tokens, put_token_behavior};
 pub use transaction::{
-    get_transaction_history, send_transaction, transaction_status, 
+    get_transaction_history, send_batch, send_transaction, transaction_status, 
     transactions_history, transactions_send
 };
-pub use wallet::{create_wallet, delete_wallet, list_wallets};
+pub use tx_wait::wait_for_transaction;
+pub use tx_ws::transaction_status_ws;
+pub use user_erasure::{erase_user, list_erasure_certificates};
+pub use wallet::{create_wallet, delete_wallet, initialize_wallet_network, list_wallets};
+pub use wallet_networks::{get_allowed_networks, put_allowed_networks};
+pub use wallet_notes::update_wallet_notes;
+pub use wallet_tokens::{create_wallet_token, list_wallet_tokens, revoke_wallet_token};
@@ -1,15 +1,17 @@
 use axum::{
     extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
+    http::HeaderMap,
     response::Json,
 };
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc};
 use tracing::error;
 
+use super::fiat::{pricing_for, value_of, Pricing};
+use crate::api::error_catalog::ApiErrorCode;
 use crate::api::middleware::extract_user::{extract_user_id_from_token, verify_wallet_ownership};
 use crate::api::server::WalletServer;
-use crate::api::types::ErrorResponse;
+use crate::api::types::ApiError;
 
 /// 多资产balancequery参数
 #[derive(Debug, Deserialize)]
@@ -25,6 +27,9 @@ pub struct MultiAssetsResponse {
     pub wallet: String,
     /// 资产balance映射 (符号 -> balance信息)
     pub balances: HashMap<String, AssetBalance>,
+    /// `fiat_value` 的计价货币；定价关闭时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_currency: Option<String>,
 }
 
 /// 单个资产balance信息
@@ -36,6 +41,9 @@ pub struct AssetBalance {
     pub symbol: String,
     /// network
     pub network: String,
+    /// 当前价格下的法币价值；价格不可用时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_value: Option<String>,
 }
 
 /// 资产符号到network的映射
@@ -59,7 +67,7 @@ pub async fn get_multi_assets(
     headers: HeaderMap,  // ✅ 启用user认证
     Path(wallet_name): Path<String>,
     Query(query): Query<MultiAssetsQuery>,
-) -> Result<Json<MultiAssetsResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<MultiAssetsResponse>, ApiError> {
     // ✅ 提取当前登录User ID
     let user_id = extract_user_id_from_token(&headers, &state).await?;
     
@@ -68,13 +76,7 @@ pub async fn get_multi_assets(
 
     // validateWallet name
     if wallet_name.is_empty() || wallet_name.contains(|c: char| !c.is_alphanumeric() && c != '_' && c != '-') {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid wallet name".to_string(),
-                code: "INVALID_WALLET_NAME".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::InvalidWalletName, "Invalid wallet name"));
     }
 
     // ✅ 非托管模式：fromuser_wallets表fetchwalletaddress
@@ -82,75 +84,59 @@ pub async fn get_multi_assets(
         .await
         .map_err(|e| {
             error!("fetchuserwalletfailed: user_id={}, error={}", user_id, e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "queryuserwalletfailed".to_string(),
-                    code: "DB_ERROR".to_string(),
-                }),
-            )
+            ApiError::new(ApiErrorCode::DbError, "queryuserwalletfailed")
         })?;
 
     // 查找指定wallet
     let wallet_info = wallets.iter()
         .find(|w| w.name == wallet_name)
         .ok_or_else(|| {
-            (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "Wallet not found".to_string(),
-                    code: "WALLET_NOT_FOUND".to_string(),
-                }),
-            )
+            ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found")
         })?;
 
     let wallet_address = wallet_info.address.as_ref()
         .ok_or_else(|| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Wallet address not found".to_string(),
-                    code: "WALLET_ADDRESS_MISSING".to_string(),
-                }),
-            )
+            ApiError::new(ApiErrorCode::WalletAddressMissing, "Wallet address not found")
         })?;
 
-    // 解析资产符号列表
-    let symbols = if let Some(symbols_str) = query.symbols {
-        symbols_str
+    let symbols = parse_symbols(query.symbols.as_deref());
+    let mut balances = asset_balances(wallet_address, &symbols).await;
+
+    let pricing = pricing_for(&state, Some(&user_id)).await;
+    if let Some(pricing) = &pricing {
+        add_fiat_values(pricing, &mut balances).await;
+    }
+
+    Ok(Json(MultiAssetsResponse {
+        wallet: wallet_name,
+        balances,
+        fiat_currency: pricing.map(|p| p.currency),
+    }))
+}
+
+/// 资产符号列表（逗号分隔）；未指定时query常见资产
+pub(crate) fn parse_symbols(symbols: Option<&str>) -> Vec<String> {
+    match symbols {
+        Some(symbols_str) => symbols_str
             .split(',')
             .map(|s| s.trim().to_uppercase())
             .filter(|s| !s.is_empty())
-            .collect::<Vec<_>>()
-    } else {
-        // 默认query常见资产
-        vec![
-            "BTC".to_string(),
-            "ETH".to_string(),
-            "USDT".to_string(),
-            "USDC".to_string(),
-        ]
-    };
-
-    // query每个资产的balance
+            .collect(),
+        None => vec!["BTC".to_string(), "ETH".to_string(), "USDT".to_string(), "USDC".to_string()],
+    }
+}
+
+/// 一个address上各资产的balance；单个资产queryfailed时记为 0，不影响其他资产
+pub(crate) async fn asset_balances(wallet_address: &str, symbols: &[String]) -> HashMap<String, AssetBalance> {
     let mut balances = HashMap::new();
 
     // ✅ 非托管模式：使用address直接query区块链balance（无需Password）
     for symbol in symbols {
-        let network = symbol_to_network(&symbol);
+        let network = symbol_to_network(symbol);
 
         // query区块链balance（模拟）
-        match query_blockchain_balance_for_asset(wallet_address, &network, &symbol).await {
-            Ok(balance) => {
-                balances.insert(
-                    symbol.clone(),
-                    AssetBalance {
-                        balance: balance.to_string(),
-                        symbol: symbol.clone(),
-                        network: network.clone(),
-                    },
-                );
-            }
+        let balance = match query_blockchain_balance_for_asset(wallet_address, &network, symbol).await {
+            Ok(balance) => balance,
             Err(e) => {
                 tracing::warn!(
                     "Failed to get balance for {} on {}: {}",
@@ -159,22 +145,28 @@ pub async fn get_multi_assets(
                     e
                 );
                 // queryfailed时返回0balance，而不是完全failed
-                balances.insert(
-                    symbol.clone(),
-                    AssetBalance {
-                        balance: "0".to_string(),
-                        symbol: symbol.clone(),
-                        network: network.clone(),
-                    },
-                );
+                "0".to_string()
             }
-        }
+        };
+        balances.insert(
+            symbol.clone(),
+            AssetBalance { balance, symbol: symbol.clone(), network, fiat_value: None },
+        );
     }
+    balances
+}
 
-    Ok(Json(MultiAssetsResponse {
-        wallet: wallet_name,
-        balances,
-    }))
+/// 法币估值：各资产并发查价，查不到价格的资产只省略 fiat_value
+pub(crate) async fn add_fiat_values(pricing: &Pricing<'_>, balances: &mut HashMap<String, AssetBalance>) {
+    let prices = futures::future::join_all(
+        balances.keys().map(|symbol| async move { (symbol.clone(), pricing.price(symbol).await) }),
+    )
+    .await;
+    for (symbol, price) in prices {
+        if let Some(asset) = balances.get_mut(&symbol) {
+            asset.fiat_value = value_of(&asset.balance, price);
+        }
+    }
 }
 
 /// query区块链资产balance（使用address）
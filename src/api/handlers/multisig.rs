@@ -2,46 +2,43 @@
 
 use axum::{
     extract::{Path, State},
-    http::{HeaderMap, StatusCode},
+    http::HeaderMap,
     response::Json,
 };
 use std::sync::Arc;
 
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::handlers::key_usage::authorize_signing;
+use crate::api::handlers::wallet_networks::check_network_allowed;
+use crate::api::handlers::wallet_tokens::ADMIN_ISSUER;
 use crate::api::middleware::authenticate;
+use crate::api::middleware::extract_user::authorize_owner_or_admin;
+use crate::api::middleware::request_metrics::note_storage_error;
 use crate::api::server::WalletServer;
 use crate::api::types::*;
-use crate::core::validation::{validate_address, validate_amount};
+use crate::api::validators::{Amount, ValidJson, ValidPath, ValidQuery, WalletNameParam};
+use crate::storage::MultisigPolicyRecord;
 
 pub async fn rotate_signing_key(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
-    Path(name): Path<String>,
-) -> Result<Json<RotateSigningKeyResponse>, (StatusCode, Json<ErrorResponse>)> {
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<RotateSigningKeyResponse>, ApiError> {
     authenticate(&headers, &state.api_key).await.map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized".to_string(),
-                code: "AUTH_FAILED".to_string(),
-            }),
-        )
+        ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
     })?;
 
-    if name.is_empty() || name.contains(|c: char| !c.is_alphanumeric() && c != '_') {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid wallet name".to_string(),
-                code: "ROTATION_FAILED".to_string(),
-            }),
-        ));
-    }
+    // wallet_manager 校验wallet存在；版本号与使用量以轮换表为准
+    let rotated = match state.wallet_manager.rotate_signing_key(name.as_str()).await {
+        Ok(_) => state.key_usage.rotate(name.as_str()).await,
+        Err(e) => Err(e),
+    };
 
-    match state.wallet_manager.rotate_signing_key(&name).await {
+    match rotated {
         Ok((old_v, new_v)) => Ok(Json(RotateSigningKeyResponse {
-            wallet: name,
-            old_version: old_v,
-            new_version: new_v,
+            wallet: name.into(),
+            old_version: old_v as u32,
+            new_version: new_v as u32,
         })),
         Err(e) => {
             // Avoid logging raw error details which may contain secrets.
@@ -53,13 +50,7 @@ pub async fn rotate_signing_key(
             } else {
                 tracing::warn!("rotate_signing_key failed: <redacted>");
             }
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to rotate signing key".to_string(),
-                    code: "ROTATION_FAILED".to_string(),
-                }),
-            ))
+            Err(ApiError::new(ApiErrorCode::RotationFailed, "Failed to rotate signing key"))
         }
     }
 }
@@ -67,107 +58,29 @@ pub async fn rotate_signing_key(
 pub async fn send_multi_sig_transaction(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
-    Path(name): Path<String>,
-    Json(payload): Json<MultiSigTransactionRequest>,
-) -> Result<Json<TransactionResponse>, (StatusCode, Json<ErrorResponse>)> {
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<MultiSigTransaction>,
+) -> Result<Json<TransactionResponse>, ApiError> {
     authenticate(&headers, &state.api_key).await.map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized".to_string(),
-                code: "AUTH_FAILED".to_string(),
-            }),
-        )
+        ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
     })?;
 
-    // Validate wallet name
-    if name.is_empty() || name.contains(|c: char| !c.is_alphanumeric() && c != '_') {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid wallet name".to_string(),
-                code: "MULTI_SIG_FAILED".to_string(),
-            }),
-        ));
-    }
-
     // Check signatures first (as per test expectations)
     if payload.signatures.len() < state.config.multi_sig_threshold as usize {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Insufficient signatures".to_string(),
-                code: "MULTI_SIG_FAILED".to_string(),
-            }),
-        ));
-    }
-
-    // Validate required parameters
-    if payload.to.is_empty() || payload.amount.is_empty() || payload.network.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Missing required parameters".to_string(),
-                code: "MULTI_SIG_FAILED".to_string(),
-            }),
-        ));
-    }
-
-    // Validate address format based on network
-    if let Err(_e) = validate_address(&payload.to, &payload.network) {
-        // ✅ 不泄漏validate细节
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid address format".to_string(),
-                code: "MULTI_SIG_FAILED".to_string(),
-            }),
-        ));
-    }
-
-    // Validate amount
-    if let Err(_e) = validate_amount(&payload.amount) {
-        // ✅ 不泄漏validate细节
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid amount format".to_string(),
-                code: "MULTI_SIG_FAILED".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::InsufficientSignatures, "Insufficient signatures"));
     }
 
-    // Validate network support
-    if !matches!(
-        payload.network.as_str(),
-        "eth" | "sepolia" | "bsc" | "polygon-testnet"
-    ) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Unsupported network".to_string(),
-                code: "MULTI_SIG_FAILED".to_string(),
-            }),
-        ));
-    }
-
-    if payload.signatures.len() < state.config.multi_sig_threshold as usize {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Insufficient signatures".to_string(),
-                code: "MULTI_SIG_FAILED".to_string(),
-            }),
-        ));
-    }
+    let name = name.as_str();
+    check_network_allowed(&state, name, payload.network.as_str())?;
+    authorize_signing(&state, name).await?;
 
     let threshold = payload.signatures.len() as u32;
     match state
         .wallet_manager
         .send_multi_sig_transaction(
-            &name,
-            &payload.to,
-            &payload.amount,
+            name,
+            payload.to.as_str(),
+            payload.amount.as_str(),
             &payload.signatures,
             threshold,
         )
@@ -177,18 +90,135 @@ pub async fn send_multi_sig_transaction(
             tx_id: tx_hash.clone(), 
             tx_hash: Some(tx_hash.clone()), 
             status: "sent".to_string(),
-            network: payload.network.clone(),
+            network: payload.network.to_string(),
             timestamp: chrono::Utc::now().to_rfc3339(),
             fee: "0.0".to_string(),
             confirmations: "0".to_string(),
+            explorer_url: state.config.blockchain.explorer_tx_url(payload.network.as_str(), &tx_hash),
         })),
-        Err(_) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "Failed to send multi-sig transaction".to_string(),
-                code: "MULTI_SIG_FAILED".to_string(),
-            }),
-        )),
+        Err(_) => Err(ApiError::new(ApiErrorCode::MultiSigFailed, "Failed to send multi-sig transaction")),
     }
 }
 
+
+
+fn policy_storage_error(e: anyhow::Error) -> ApiError {
+    tracing::error!("multisig policy storage error: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access multisig policy")
+}
+
+/// 整单位金额换算为 wei；超过 u128 的金额不可能落在任何档位内
+fn to_minimal_units(amount: &Amount) -> Result<u128, ApiError> {
+    ethers::utils::parse_ether(amount.as_str())
+        .ok()
+        .filter(|wei| *wei <= ethers::types::U256::from(u128::MAX))
+        .map(|wei| wei.as_u128())
+        .ok_or_else(|| ApiError::new(ApiErrorCode::InvalidAmount, "Amount is too large"))
+}
+
+/// `PUT /api/wallets/:name/multisig/policy`：替换该wallet在某network上的分档策略
+///
+/// 已创建的提案保留各自冻结的阈值，不受影响。
+pub async fn put_multisig_policy(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<MultisigPolicyUpdate>,
+) -> Result<Json<MultisigPolicyRecord>, ApiError> {
+    let name = name.as_str();
+    let updated_by = authorize_owner_or_admin(&headers, &state, name)
+        .await?
+        .unwrap_or_else(|| ADMIN_ISSUER.to_string());
+
+    let record = MultisigPolicyRecord {
+        wallet_name: name.to_string(),
+        network: payload.network.as_str().to_string(),
+        signers: payload.signers,
+        tiers: payload.policy.tiers().to_vec(),
+        updated_by,
+        updated_at: chrono::Utc::now().timestamp(),
+    };
+    state.storage.put_multisig_policy(&record).await.map_err(policy_storage_error)?;
+    tracing::info!("multisig policy for {} on {} updated by {}", name, record.network, record.updated_by);
+    Ok(Json(record))
+}
+
+/// `GET /api/wallets/:name/multisig/policy?network=eth`
+pub async fn get_multisig_policy(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(query): ValidQuery<NetworkQuery>,
+) -> Result<Json<MultisigPolicyRecord>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    load_policy(&state, name, query.network.as_str()).await.map(Json)
+}
+
+async fn load_policy(
+    state: &WalletServer,
+    name: &str,
+    network: &str,
+) -> Result<MultisigPolicyRecord, ApiError> {
+    state
+        .storage
+        .get_multisig_policy(name, network)
+        .await
+        .map_err(policy_storage_error)?
+        .ok_or_else(|| {
+            ApiError::new(
+                ApiErrorCode::MultisigPolicyNotFound,
+                format!("No multisig policy for {} on {}", name, network),
+            )
+        })
+}
+
+/// `POST /api/wallets/:name/multisig/proposals`：按当前策略冻结所需sign数
+pub async fn create_multisig_proposal(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<MultisigProposal>,
+) -> Result<Json<MultisigProposalResponse>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+
+    let network = payload.network.as_str();
+    let record = load_policy(&state, name, network).await?;
+    let policy = record.policy().map_err(policy_storage_error)?;
+    let signers = record
+        .signers
+        .iter()
+        .map(|key| key.parse::<secp256k1::PublicKey>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| policy_storage_error(anyhow::anyhow!("stored signer key: {}", e)))?;
+    let amount = to_minimal_units(&payload.amount)?;
+
+    let mut multisig = state.multisig.lock().await;
+    let id = multisig
+        .create_tiered_transaction(name, payload.to.as_str(), amount, network, signers, &policy)
+        .map_err(|e| ApiError::new(ApiErrorCode::AmountExceedsPolicy, e.to_string()))?;
+    let tx = multisig
+        .get_transaction(&id)
+        .ok_or_else(|| policy_storage_error(anyhow::anyhow!("proposal {} vanished after creation", id)))?;
+    Ok(Json(MultisigProposalResponse::from(tx)))
+}
+
+/// `GET /api/wallets/:name/multisig/proposals/:id`
+pub async fn get_multisig_proposal(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((name, id)): Path<(String, String)>,
+) -> Result<Json<MultisigProposalResponse>, ApiError> {
+    let name = WalletNameParam::try_from(name.as_str())?;
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+
+    let multisig = state.multisig.lock().await;
+    multisig
+        .get_transaction(&id)
+        .filter(|tx| tx.wallet.as_deref() == Some(name))
+        .map(|tx| Json(MultisigProposalResponse::from(tx)))
+        .ok_or_else(|| ApiError::new(ApiErrorCode::ProposalNotFound, "Proposal not found"))
+}
@@ -0,0 +1,79 @@
+//! `GET /api/networks`：已配置network一览（UI 的network选择器一次取齐）
+//!
+//! 健康状态只看本地信息，不发起 RPC：没有客户端为 `unavailable`，
+//! 熔断打开为 `down`，否则 `ok`。
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+use super::funding::native_symbol;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::wallet_scope::extract_wallet_caller;
+use crate::api::server::WalletServer;
+use crate::api::types::ApiError;
+use crate::core::config::ExplorerUrlTemplate;
+
+#[derive(Debug, Serialize)]
+pub struct NetworkInfo {
+    /// 配置键，即其他接口里的 `network` 参数
+    pub id: String,
+    pub name: String,
+    pub chain_id: u64,
+    pub symbol: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explorer_base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explorer_url_template: Option<ExplorerUrlTemplate>,
+    pub required_confirmations: u64,
+    /// `ok` | `down` | `unavailable`
+    pub health: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworksResponse {
+    pub networks: Vec<NetworkInfo>,
+}
+
+/// `GET /api/networks`（API key、会话或wallet token 均可）
+pub async fn list_networks(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+) -> Result<Json<NetworksResponse>, ApiError> {
+    if authenticate(&headers, &state.api_key).await.is_err() {
+        extract_wallet_caller(&headers, &state).await?;
+    }
+
+    let mut networks: Vec<NetworkInfo> = state
+        .config
+        .blockchain
+        .networks
+        .iter()
+        .map(|(id, network)| {
+            let health = if state.chain_clients.get(id).is_err() {
+                "unavailable"
+            } else if state.circuit_breaker.is_open(id) {
+                "down"
+            } else {
+                "ok"
+            };
+            NetworkInfo {
+                id: id.clone(),
+                name: network.name.clone(),
+                chain_id: network.chain_id,
+                symbol: native_symbol(id),
+                explorer_base: network.explorer_url_template.as_ref().map(|t| t.base().to_string()),
+                explorer_url_template: network.explorer_url_template.clone(),
+                required_confirmations: network.required_confirmations(),
+                health,
+            }
+        })
+        .collect();
+    networks.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(Json(NetworksResponse { networks }))
+}
@@ -0,0 +1,84 @@
+//! nonce 对账 handler
+//!
+//! 被 mempool 丢弃的transaction会让已预留的 nonce 空置，之后的发送都卡在它后面。
+//! `POST /api/wallets/:name/nonces/reconcile` 比对链上transaction数与存储的计数器，
+//! 把长时间未上链的预留归还，无法归还的作为 gap 报告。wallet Password通过
+//! `X-Wallet-Password` 头传入（用于得出服务端signaddress）。
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use std::sync::Arc;
+use tracing::{error, warn};
+use zeroize::Zeroizing;
+
+use super::btc_descriptor::WALLET_PASSWORD_HEADER;
+use super::deadman::unlock_error;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::authorize_owner_or_admin;
+use crate::api::middleware::request_metrics::note_wallet_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ParamError, ValidJson, ValidPath, WalletNameParam};
+use crate::core::errors::WalletError;
+use crate::core::wallet_manager::nonce::NonceReconciliation;
+
+fn reconcile_error(name: &str, e: WalletError) -> ApiError {
+    match e {
+        WalletError::NetworkError(msg) | WalletError::NotImplemented(msg) => {
+            warn!("nonce reconciliation of {}: transaction count unavailable: {}", name, msg);
+            ApiError::new(ApiErrorCode::NonceLookupFailed, "The transaction count could not be read from the node")
+        }
+        WalletError::StorageError(msg) => {
+            error!("nonce reconciliation of {}: storage failed: {}", name, msg);
+            note_wallet_error(&WalletError::StorageError(msg));
+            ApiError::new(ApiErrorCode::DbError, "Failed to access the nonce store")
+        }
+        other => unlock_error(name, other),
+    }
+}
+
+/// `POST /api/wallets/:name/nonces/reconcile`：wallet owner 或 admin
+pub async fn reconcile_nonces(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(req): ValidJson<NonceReconcileParams>,
+) -> Result<Json<NonceReconciliation>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    let password = headers
+        .get(WALLET_PASSWORD_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| Zeroizing::new(v.to_string()))
+        .ok_or(ParamError::Missing("X-Wallet-Password header"))?;
+
+    let chain = state.signing_intents.chain();
+    let report = state
+        .wallet_manager
+        .reconcile_nonces(
+            name,
+            &password,
+            req.network.as_str(),
+            &state.storage,
+            chain.as_ref(),
+            chrono::Duration::seconds(req.min_age_secs as i64),
+        )
+        .await
+        .map_err(|e| reconcile_error(name, e))?;
+
+    if !report.released.is_empty() {
+        let details = serde_json::json!({
+            "network": report.network,
+            "address": report.address,
+            "on_chain": report.on_chain,
+            "released": report.released,
+        });
+        if let Err(e) = state.storage.log_action(name, "nonces_released", &details.to_string(), None, None).await {
+            error!("failed to audit nonce release of {}: {}", name, e);
+        }
+    }
+    Ok(Json(report))
+}
@@ -0,0 +1,224 @@
+//! EIP-681 付款链接：解析客户发来的链接、为收款wallet生成链接
+//!
+//! 解析结果中的network按链接的 chain id 在已配置network中查找，金额同时给出最小
+//! 单位与十进制显示；ERC-20 金额只有在请求给出 `token_decimals` 时才换算显示。
+//! 生成的链接总是规范形式（EIP-55 address、带 chain id、整数金额），再解析可得到
+//! 同一个付款意图。
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use ethers::types::{Address, U256};
+use ethers::utils::to_checksum;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::address::stored_wallet_address;
+use super::inspect::is_admin_caller;
+use super::wallet_networks::check_network_allowed;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::{extract_user_id_from_token, verify_wallet_ownership};
+use crate::api::server::WalletServer;
+use crate::api::types::ApiError;
+use crate::api::validators::{
+    Amount, NetworkName, ParamError, ValidJson, ValidPath, ValidQuery, Validate, WalletNameParam,
+};
+use crate::cli::amounts::Denomination;
+use crate::core::amount::{Amount as Minimal, AssetTag};
+use crate::core::validation::eip681::{parse_payment_uri, PaymentIntent};
+
+/// EVM 原生币的小数位
+const NATIVE_DECIMALS: u32 = 18;
+/// `uint256` 最多 78 位十进制数，更多的小数位没有意义
+const MAX_TOKEN_DECIMALS: u32 = 77;
+
+#[derive(Debug, Deserialize)]
+pub struct ParsePaymentUriRequest {
+    pub uri: String,
+    /// ERC-20 代币的小数位；缺省时只返回最小单位金额
+    pub token_decimals: Option<u32>,
+}
+
+/// [`ParsePaymentUriRequest`] validate后
+pub struct ParsePaymentUri {
+    pub intent: PaymentIntent,
+    pub token_decimals: Option<u32>,
+}
+
+impl Validate for ParsePaymentUri {
+    type Raw = ParsePaymentUriRequest;
+
+    fn validate(raw: ParsePaymentUriRequest) -> Result<Self, ParamError> {
+        if raw.uri.trim().is_empty() {
+            return Err(ParamError::Missing("uri"));
+        }
+        if raw.token_decimals.is_some_and(|d| d > MAX_TOKEN_DECIMALS) {
+            return Err(ParamError::PaymentUri(format!("token_decimals must be at most {}", MAX_TOKEN_DECIMALS)));
+        }
+        let intent = parse_payment_uri(&raw.uri).map_err(|e| ParamError::PaymentUri(e.to_string()))?;
+        Ok(Self { intent, token_decimals: raw.token_decimals })
+    }
+}
+
+/// `GET /api/wallets/:name/payment_uri?network=eth&amount=1.5`
+#[derive(Debug, Deserialize)]
+pub struct PaymentUriQuery {
+    /// 默认 eth
+    pub network: Option<String>,
+    /// 主单位十进制金额；缺省时由付款方填写
+    pub amount: Option<String>,
+}
+
+/// [`PaymentUriQuery`] validate后
+pub struct PaymentUriParams {
+    pub network: NetworkName,
+    pub amount: Option<Amount>,
+}
+
+impl Validate for PaymentUriParams {
+    type Raw = PaymentUriQuery;
+
+    fn validate(raw: PaymentUriQuery) -> Result<Self, ParamError> {
+        let network = NetworkName::try_from(raw.network.as_deref().unwrap_or("eth"))?.require_evm()?;
+        let amount = raw.amount.as_deref().map(Amount::try_from).transpose()?;
+        Ok(Self { network, amount })
+    }
+}
+
+/// 解码后的付款意图
+#[derive(Debug, Serialize)]
+pub struct PaymentUriResponse {
+    /// 规范形式的链接
+    pub uri: String,
+    /// `native` 或 `erc20`
+    pub kind: &'static str,
+    /// 收款address（EIP-55）；ERC-20 为 `address` 参数而不是合约
+    pub recipient: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    pub chain_id: Option<u64>,
+    /// 由 chain id 解析出的已配置network；链接未指定 chain id 时为 null
+    pub network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<&'static str>,
+    /// 最小单位（wei / 代币最小单位）
+    pub amount_minimal: Option<String>,
+    /// 主单位十进制金额
+    pub amount: Option<String>,
+}
+
+/// 已配置且 chain id 为 `chain_id` 的 EVM network
+pub(crate) fn network_for_chain(state: &WalletServer, chain_id: u64) -> Result<NetworkName, ParamError> {
+    let name = state.config.blockchain.network_for_chain_id(chain_id).ok_or(ParamError::ChainIdUnknown(chain_id))?;
+    NetworkName::try_from(name).and_then(NetworkName::require_evm).map_err(|_| ParamError::ChainIdUnknown(chain_id))
+}
+
+/// 最小单位 → 主单位十进制串（去掉末尾的 0）
+fn decimal_display(amount: U256, decimals: u32) -> String {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (int, frac) = padded.split_at(padded.len() - decimals);
+    match frac.trim_end_matches('0') {
+        "" => int.to_string(),
+        frac => format!("{}.{}", int, frac),
+    }
+}
+
+fn describe(
+    state: &WalletServer,
+    intent: &PaymentIntent,
+    token_decimals: Option<u32>,
+) -> Result<PaymentUriResponse, ParamError> {
+    let network = intent.chain_id.map(|id| network_for_chain(state, id)).transpose()?;
+    let (decimals, symbol) = match intent.token {
+        None => (
+            Some(NATIVE_DECIMALS),
+            network.and_then(|n| Denomination::for_network(n.as_str())).map(|d| d.symbol),
+        ),
+        Some(_) => (token_decimals, None),
+    };
+    Ok(PaymentUriResponse {
+        uri: intent.to_uri(),
+        kind: if intent.is_native() { "native" } else { "erc20" },
+        recipient: to_checksum(&intent.recipient, None),
+        token: intent.token.map(|t| to_checksum(&t, None)),
+        chain_id: intent.chain_id,
+        network: network.map(|n| n.as_str().to_string()),
+        symbol,
+        amount_minimal: intent.amount.map(|a| a.to_string()),
+        amount: intent.amount.zip(decimals).map(|(a, d)| decimal_display(a, d)),
+    })
+}
+
+/// `POST /api/parse/payment_uri`
+pub async fn decode_payment_uri(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidJson(request): ValidJson<ParsePaymentUri>,
+) -> Result<Json<PaymentUriResponse>, ApiError> {
+    // 任意已认证调用方（admin、会话或只读 token）
+    is_admin_caller(&headers, &state).await?;
+    Ok(Json(describe(&state, &request.intent, request.token_decimals)?))
+}
+
+/// `GET /api/wallets/:name/payment_uri`：向该wallet付款的规范链接
+pub async fn wallet_payment_uri(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(query): ValidQuery<PaymentUriParams>,
+) -> Result<Json<PaymentUriResponse>, ApiError> {
+    let user_id = extract_user_id_from_token(&headers, &state).await?;
+    let name = name.as_str();
+    verify_wallet_ownership(&user_id, name, &state).await?;
+    let network = query.network.as_str();
+    check_network_allowed(&state, name, network)?;
+
+    let chain_id = state.config.blockchain.networks.get(network).map(|n| n.chain_id).ok_or_else(|| {
+        ApiError::new(ApiErrorCode::UnsupportedNetwork, format!("Network {} is not configured", network))
+    })?;
+    let recipient: Address = stored_wallet_address(&state, &user_id, name).await?.parse().map_err(|_| {
+        ApiError::new(ApiErrorCode::UnsupportedWalletKind, "Wallet has no EVM address")
+    })?;
+    let amount = query
+        .amount
+        .map(|a| {
+            Minimal::parse(a.as_str(), NATIVE_DECIMALS, AssetTag::new(network, ""))
+                .map(|a| U256::from(a.minimal()))
+                .map_err(|_| ParamError::AmountTooLong)
+        })
+        .transpose()?;
+
+    let intent = PaymentIntent::native(recipient, Some(chain_id), amount);
+    Ok(Json(describe(&state, &intent, None)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_display() {
+        assert_eq!(decimal_display(U256::exp10(18), 18), "1");
+        assert_eq!(decimal_display(U256::from(1_500_000_000_000_000_000u128), 18), "1.5");
+        assert_eq!(decimal_display(U256::from(1u64), 18), "0.000000000000000001");
+        assert_eq!(decimal_display(U256::from(2_500_000u64), 6), "2.5");
+        assert_eq!(decimal_display(U256::zero(), 6), "0");
+        assert_eq!(decimal_display(U256::from(42u64), 0), "42");
+    }
+
+    #[test]
+    fn test_payment_uri_params() {
+        let params = PaymentUriParams::validate(PaymentUriQuery { network: None, amount: Some("1.5".into()) }).unwrap();
+        assert_eq!(params.network.as_str(), "eth");
+        assert_eq!(params.amount.unwrap().as_str(), "1.5");
+        let btc = PaymentUriQuery { network: Some("btc".into()), amount: None };
+        assert_eq!(PaymentUriParams::validate(btc).err(), Some(ParamError::NetworkNotAllowed("btc")));
+    }
+}
@@ -0,0 +1,120 @@
+//! 链上对账 handlers（API key）
+//!
+//! 事故（RPC 故障、实例崩溃）之后，`POST` 为一个时间范围登记对账：后台任务
+//! [`RECONCILIATION_JOB`] 逐条核对该范围内的transaction记录与链上回执，并用回填的
+//! 扫描机制找出我方address上链却没有记录的transaction。`apply` 只修正安全的差异，
+//! 其余只出报告。`GET .../:id` 返回运行状态、进度与分页的差异列表。
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use std::sync::Arc;
+use tracing::{debug, error, info};
+
+use super::admin::{unauthorized, MAX_ADMIN_PAGE_SIZE};
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::pagination::{PageCursor, PageDirection};
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ParamError, ValidJson, ValidQuery};
+use crate::ops::incidents::spawn_named;
+use crate::ops::jobs::JobRunError;
+use crate::ops::reconciliation::RECONCILIATION_JOB;
+use crate::storage::{NewReconciliationRun, RUN_COMPLETED};
+
+/// 差异按发现顺序（position）升序分页
+#[derive(Debug, Clone, Copy)]
+pub struct ReconciliationPages;
+
+impl PageScope for ReconciliationPages {
+    const SCOPE: &'static str = "reconciliation_discrepancies";
+    const DIRECTION: PageDirection = PageDirection::Asc;
+    const DEFAULT_LIMIT: usize = 100;
+    const MAX_LIMIT: usize = MAX_ADMIN_PAGE_SIZE;
+}
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("reconciliation storage failed: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access reconciliation runs")
+}
+
+/// `POST /api/admin/reconciliations`：登记后立即唤起后台任务，返回 202 与排队中的运行
+pub async fn create_reconciliation(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidJson(req): ValidJson<ReconciliationParams>,
+) -> Result<(StatusCode, Json<ReconciliationResponse>), ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    if let Some(unknown) = req.networks.iter().find(|n| !state.config.blockchain.networks.contains_key(*n)) {
+        return Err(ApiError::new(ApiErrorCode::UnknownNetwork, format!("Unknown network: {}", unknown)));
+    }
+
+    let run = state
+        .storage
+        .queue_reconciliation(&NewReconciliationRun {
+            range_from: req.from,
+            range_to: req.to,
+            networks: &req.networks,
+            apply: req.apply,
+            requested_by: &req.requested_by,
+        })
+        .await
+        .map_err(storage_error)?;
+    info!("reconciliation {} of {}..{} queued by {} (apply {})", run.id, req.from, req.to, req.requested_by, req.apply);
+    let details = serde_json::json!({
+        "run_id": run.id,
+        "from": req.from,
+        "to": req.to,
+        "networks": req.networks,
+        "apply": req.apply,
+        "requested_by": req.requested_by,
+    })
+    .to_string();
+    if let Err(e) = state.storage.log_action("system", "reconciliation.requested", &details, None, None).await {
+        error!("failed to audit reconciliation request {}: {}", run.id, e);
+    }
+
+    // 任务正在运行时，本轮处理完手头的运行后会继续认领新登记的
+    let jobs = state.jobs.clone();
+    spawn_named("reconciliation_trigger", async move {
+        match jobs.run_now(RECONCILIATION_JOB).await {
+            Ok(_) | Err(JobRunError::AlreadyRunning(_)) => {}
+            Err(e) => debug!("reconciliation not triggered: {}", e),
+        }
+    });
+    Ok((StatusCode::ACCEPTED, Json(ReconciliationResponse { run, progress: None, findings: None })))
+}
+
+/// `GET /api/admin/reconciliations/:id?cursor=&limit=`
+pub async fn get_reconciliation(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    ValidQuery(request): ValidQuery<PageRequest<ReconciliationPages>>,
+) -> Result<Json<ReconciliationResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let run = state.storage.reconciliation_run(&id).await.map_err(storage_error)?.ok_or_else(|| {
+        ApiError::new(ApiErrorCode::ReconciliationNotFound, "Reconciliation run not found")
+    })?;
+    if run.status != RUN_COMPLETED {
+        let progress = state.reconciliation.progress_of(&run.id);
+        return Ok(Json(ReconciliationResponse { run, progress, findings: None }));
+    }
+
+    let after = request.cursor.as_ref().map(|c| c.as_int().ok_or(ParamError::Cursor)).transpose()?;
+    let mut items =
+        state.storage.reconciliation_discrepancies(&run.id, after, request.limit + 1).await.map_err(storage_error)?;
+    let next = if items.len() > request.limit {
+        items.truncate(request.limit);
+        items.last().map(|d| PageCursor::int(PageDirection::Asc, d.position))
+    } else {
+        None
+    };
+    let page = request.page(items, next, Some(run.totals.discrepancies)).map_err(storage_error)?;
+    Ok(Json(ReconciliationResponse { run, progress: None, findings: Some(page) }))
+}
@@ -0,0 +1,104 @@
+//! 元transaction中继 handlers（EIP-2771，API key）
+//!
+//! 调用方持有 API key；操作本身由user对 ForwardRequest 的 EIP-712 sign授权。
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+use super::admin::unauthorized;
+use crate::api::middleware::authenticate;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{EvmAddress, ParamError, Validate, ValidQuery};
+use crate::relay::RelayError;
+
+/// 单个 from address 最多返回的记录数
+pub const MAX_RELAY_HISTORY: i64 = 200;
+const DEFAULT_RELAY_HISTORY: i64 = 50;
+
+#[derive(Deserialize)]
+pub struct RelayHistoryQuery {
+    pub from: String,
+    pub limit: Option<i64>,
+}
+
+/// [`RelayHistoryQuery`] validate后
+pub struct RelayHistoryParams {
+    pub from: EvmAddress,
+    pub limit: i64,
+}
+
+impl Validate for RelayHistoryParams {
+    type Raw = RelayHistoryQuery;
+
+    fn validate(raw: RelayHistoryQuery) -> Result<Self, ParamError> {
+        Ok(Self {
+            from: EvmAddress::try_from(raw.from.as_str())?,
+            limit: raw.limit.unwrap_or(DEFAULT_RELAY_HISTORY).clamp(1, MAX_RELAY_HISTORY),
+        })
+    }
+}
+
+fn relay_error(e: RelayError) -> ApiError {
+    let status = match &e {
+        RelayError::Disabled => StatusCode::SERVICE_UNAVAILABLE,
+        RelayError::InvalidRequest(_) | RelayError::Expired { .. } => StatusCode::BAD_REQUEST,
+        RelayError::SignatureMismatch | RelayError::Blocked(_) => StatusCode::FORBIDDEN,
+        RelayError::QuotaExhausted { .. } => StatusCode::TOO_MANY_REQUESTS,
+        RelayError::Duplicate => StatusCode::CONFLICT,
+        RelayError::Submission(_) => StatusCode::BAD_GATEWAY,
+        RelayError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let message = match &e {
+        RelayError::Storage(inner) => {
+            error!("meta-transaction relay storage error: {}", inner);
+            "Failed to record relay".to_string()
+        }
+        other => other.to_string(),
+    };
+    ApiError::from_code(status, e.code(), message)
+}
+
+fn parse_signature(signature: &str) -> Result<Vec<u8>, RelayError> {
+    let hex_sig = signature.strip_prefix("0x").unwrap_or(signature);
+    match hex::decode(hex_sig) {
+        Ok(bytes) if bytes.len() == 65 => Ok(bytes),
+        _ => Err(RelayError::InvalidRequest("signature must be 65 bytes of hex".to_string())),
+    }
+}
+
+/// `POST /api/relay/meta_tx`：validate并代付 gas 提交user的 ForwardRequest
+pub async fn relay_meta_tx(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(payload): Json<MetaTxRelayRequest>,
+) -> Result<Json<MetaTxRelayResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let signature = parse_signature(&payload.signature).map_err(relay_error)?;
+    let receipt = state.relay.relay(&payload.request, &signature).await.map_err(relay_error)?;
+    Ok(Json(receipt))
+}
+
+/// `GET /api/relay/meta_tx?from=0x...&limit=50`：某个user address 的中继记录
+pub async fn list_meta_tx_relays(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidQuery(query): ValidQuery<RelayHistoryParams>,
+) -> Result<Json<MetaTxRelayListResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let from = query.from.as_str().to_ascii_lowercase();
+    let relays = state
+        .storage
+        .meta_tx_relays_for(&from, query.limit)
+        .await
+        .map_err(|e| relay_error(RelayError::Storage(e)))?;
+    Ok(Json(MetaTxRelayListResponse { relays }))
+}
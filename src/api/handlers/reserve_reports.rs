@@ -0,0 +1,233 @@
+//! 储备证明（proof of reserves）报告
+//!
+//! admin 生成：每个配置的network先固定一个区块高度，再在该高度读取全部托管wallet
+//! （或某个wallet组的成员）的原生币与配置代币余额，用 `reserve_reports.signing_wallet`
+//! 的密钥按 EIP-191 签署报告的 canonical JSON 后存档。读取失败的余额记为
+//! `unverified` 行，不会被省略。
+//!
+//! `POST /api/reports/verify` 只做计算：恢复签名者并由行数据重算合计，不查存档；
+//! 审计方也可以用 `ops::proof_of_reserves::verify_report` 离线完成同样的validate。
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use super::admin::unauthorized;
+use super::deadman::unlock_error;
+use super::groups::{load_group, member_wallets};
+use super::inspect::is_admin_caller;
+use super::key_usage::authorize_signing;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ValidJson, WalletNameParam};
+use crate::core::config::ReserveReportConfig;
+use crate::ops::proof_of_reserves::{
+    collect_reserves, verify_report, ReportVerification, ReserveAsset, ReserveReport, ReserveWallet,
+};
+use crate::pricing::native_symbol;
+use crate::storage::{NewReserveReport, ReserveReportRecord};
+
+/// 单页最大条数
+const MAX_REPORT_PAGE_SIZE: usize = 200;
+const DEFAULT_REPORT_PAGE_SIZE: usize = 50;
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("reserve report storage failed: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access reserve reports")
+}
+
+fn user_db_error(e: anyhow::Error) -> ApiError {
+    error!("reserve report wallet lookup failed: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to query user wallets")
+}
+
+/// 报告覆盖的wallet；没有 EVM address的wallet（如 btc）不在报告范围内
+async fn reserve_wallets(
+    state: &WalletServer,
+    group: Option<&WalletNameParam>,
+) -> Result<Vec<ReserveWallet>, ApiError> {
+    let linked: Vec<(String, String)> = match group {
+        Some(group) => {
+            let group = load_group(state, group.as_str(), None).await?;
+            member_wallets(state, &group)
+                .await?
+                .into_iter()
+                .filter_map(|(name, info)| Some((name, info?.address?)))
+                .collect()
+        }
+        None => state.user_db.list_linked_wallets().await.map_err(user_db_error)?,
+    };
+    let mut wallets: Vec<ReserveWallet> = linked
+        .into_iter()
+        .filter_map(|(name, address)| match address.parse() {
+            Ok(address) => Some(ReserveWallet { name, address }),
+            Err(_) => {
+                info!("reserve report: wallet {} has no EVM address, skipped", name);
+                None
+            }
+        })
+        .collect();
+    // 多个user可能关联同名同address的wallet，只统计一次
+    wallets.dedup_by(|a, b| a.name == b.name && a.address == b.address);
+    Ok(wallets)
+}
+
+/// 每个network的原生币，加上配置的代币；无效的合约address跳过并告警
+fn reserve_assets(config: &ReserveReportConfig) -> Vec<ReserveAsset> {
+    let mut assets = Vec::new();
+    for network in &config.networks {
+        // 没有已知符号的network（测试网等）记为 NATIVE
+        let symbol = native_symbol(network).unwrap_or("NATIVE").to_string();
+        assets.push(ReserveAsset { network: network.clone(), symbol, token: None });
+        for (symbol, address) in config.tokens.get(network).into_iter().flatten() {
+            match address.trim().parse() {
+                Ok(token) => {
+                    assets.push(ReserveAsset { network: network.clone(), symbol: symbol.clone(), token: Some(token) })
+                }
+                Err(e) => warn!("reserve report: skipping token {} on {} ({}): {}", symbol, network, address, e),
+            }
+        }
+    }
+    assets
+}
+
+fn document_of(record: &ReserveReportRecord) -> Result<ReserveReportDocument, ApiError> {
+    let report = serde_json::from_str(&record.document).map_err(|e| storage_error(e.into()))?;
+    Ok(ReserveReportDocument {
+        id: Some(record.id.clone()),
+        scheme: "eip191".to_string(),
+        signer: record.signer.clone(),
+        signature: record.signature.clone(),
+        report,
+    })
+}
+
+/// `POST /api/admin/reports/proof_of_reserves`：API key，需要签名wallet的Password
+pub async fn create_reserve_report(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidJson(params): ValidJson<ReserveReportParams>,
+) -> Result<Json<ReserveReportDocument>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let config = &state.config.reserve_reports;
+    let signing_wallet = config.signing_wallet.as_deref().ok_or_else(|| {
+        ApiError::new(
+            ApiErrorCode::ReportingKeyNotConfigured,
+            "No reporting key configured (reserve_reports.signing_wallet)",
+        )
+    })?;
+    // 先解锁签名密钥：Password错误时不必读取任何余额
+    let signer = state
+        .wallet_manager
+        .message_signer(signing_wallet, &params.password, "eth")
+        .await
+        .map_err(|e| unlock_error(signing_wallet, e))?;
+    authorize_signing(&state, signing_wallet).await?;
+
+    let wallets = reserve_wallets(&state, params.group.as_ref()).await?;
+    let assets = reserve_assets(config);
+    let (block_heights, rows) =
+        collect_reserves(state.gas_oracle.as_ref(), &wallets, &assets, config.concurrency).await;
+    let group = params.group.map(|g| g.as_str().to_string());
+    let generated_at = state.storage.clock().now().to_rfc3339();
+    let report = ReserveReport::new(generated_at, group, block_heights, rows).map_err(|e| {
+        error!("reserve report totals failed: {}", e);
+        ApiError::new(ApiErrorCode::ReportFailed, "Failed to build reserve report")
+    })?;
+    let signing_failed = |e: crate::core::errors::WalletError| {
+        error!("signing reserve report failed: {}", e);
+        ApiError::new(ApiErrorCode::SigningFailed, "Failed to sign reserve report")
+    };
+    // 存档的正是被签名的字节
+    let document = report.canonical_json().map_err(signing_failed)?;
+    let signature = signer.sign(document.as_bytes()).map_err(signing_failed)?;
+
+    let unverified_rows = report.unverified_rows();
+    let id = state
+        .storage
+        .record_reserve_report(&NewReserveReport {
+            group_name: report.group.as_deref(),
+            signer: signer.address(),
+            signature: &signature,
+            document: &document,
+            rows: report.rows.len() as i64,
+            unverified_rows: unverified_rows as i64,
+        })
+        .await
+        .map_err(storage_error)?;
+    let details = serde_json::json!({
+        "report_id": id,
+        "group": report.group,
+        "rows": report.rows.len(),
+        "unverified_rows": unverified_rows,
+        "block_heights": report.block_heights,
+    });
+    if let Err(e) =
+        state.storage.log_action(signing_wallet, "reserve_report.created", &details.to_string(), None, None).await
+    {
+        error!("failed to audit reserve report {}: {}", id, e);
+    }
+    info!("reserve report {} signed: {} rows, {} unverified", id, report.rows.len(), unverified_rows);
+
+    Ok(Json(ReserveReportDocument {
+        id: Some(id),
+        scheme: "eip191".to_string(),
+        signer: signer.address().to_string(),
+        signature,
+        report: serde_json::from_str(&document).map_err(|e| storage_error(e.into()))?,
+    }))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ReserveReportListQuery {
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+/// `GET /api/admin/reports/proof_of_reserves?page=1&page_size=50`
+pub async fn list_reserve_reports(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Query(query): Query<ReserveReportListQuery>,
+) -> Result<Json<ReserveReportListResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(DEFAULT_REPORT_PAGE_SIZE).clamp(1, MAX_REPORT_PAGE_SIZE);
+    let (reports, total) =
+        state.storage.reserve_reports((page - 1) * page_size, page_size).await.map_err(storage_error)?;
+    Ok(Json(ReserveReportListResponse { reports, total, page, page_size }))
+}
+
+/// `GET /api/admin/reports/proof_of_reserves/:id`：与生成时返回的文档相同
+pub async fn get_reserve_report(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ReserveReportDocument>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let record = state.storage.reserve_report(&id).await.map_err(storage_error)?.ok_or_else(|| {
+        ApiError::new(ApiErrorCode::ReserveReportNotFound, "Reserve report not found")
+    })?;
+    document_of(&record).map(Json)
+}
+
+/// `POST /api/reports/verify`：admin、会话user或带 `read_history` 的wallet token
+pub async fn verify_reserve_report(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidJson(PresentedReserveReport(document)): ValidJson<PresentedReserveReport>,
+) -> Result<Json<ReportVerification>, ApiError> {
+    is_admin_caller(&headers, &state).await?;
+    Ok(Json(verify_report(&document.report, &document.signer, &document.signature)))
+}
@@ -0,0 +1,61 @@
+//! sandbox 克隆接口（API key，仅 test / test-env 构建注册路由）
+//!
+//! 目标是本实例自己的数据库。服务仍加载着主网网络时拒绝：sandbox 标记在
+//! 启动时才生效，克隆进一个连着主网的实例等于让克隆的wallet出现在主网上。
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use std::sync::Arc;
+use tracing::{error, warn};
+
+use super::admin::unauthorized;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::core::errors::WalletError;
+use crate::ops::sandbox_clone::SandboxClone;
+use crate::storage::CloneSource;
+
+/// `POST /api/admin/sandbox-clone`：把源数据库中的wallet克隆进本实例，返回克隆报告
+pub async fn sandbox_clone(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Json(req): Json<SandboxCloneRequest>,
+) -> Result<Json<SandboxCloneResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let mainnets: Vec<&String> =
+        state.config.blockchain.networks.iter().filter(|(_, n)| n.is_mainnet()).map(|(name, _)| name).collect();
+    if !mainnets.is_empty() {
+        return Err(ApiError::new(
+            ApiErrorCode::SandboxMainnetConfigured,
+            format!("mainnet networks are configured ({:?}); start the server on a sandbox database", mainnets),
+        ));
+    }
+    let source = CloneSource::open(&req.source_db)
+        .await
+        .map_err(|e| ApiError::new(ApiErrorCode::InvalidSourceDb, e.to_string()))?;
+
+    let mut clone = SandboxClone::new(req.password);
+    if let Some(wallets) = req.wallets {
+        clone = clone.with_wallets(wallets);
+    }
+    let result = clone.run(&source, &state.storage, &state.wallet_manager, &state.chain_clients).await;
+    source.close().await;
+    match result {
+        Ok(report) => Ok(Json(report)),
+        Err(WalletError::NotFoundError(e)) => Err(ApiError::new(ApiErrorCode::WalletNotFound, e)),
+        Err(WalletError::ValidationError(e)) => {
+            warn!("sandbox clone refused: {}", e);
+            Err(ApiError::new(ApiErrorCode::SandboxCloneRefused, e))
+        }
+        Err(e) => {
+            error!("sandbox clone failed: {}", e);
+            Err(ApiError::new(ApiErrorCode::SandboxCloneFailed, "Sandbox clone failed"))
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! wallet支出上限 handlers
+//!
+//! 上限按wallet与network保存，单位为最小单位（wei）；接口上以整单位十进制收发。
+//! 服务端sign的各发送路径在sign前调用 `transaction::check_spending_limit`。
+
+use axum::{extract::State, http::HeaderMap, response::Json};
+use std::sync::Arc;
+use tracing::error;
+
+use super::wallet_tokens::ADMIN_ISSUER;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::extract_user::authorize_owner_or_admin;
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ValidJson, ValidPath, WalletNameParam};
+use crate::core::amount::Amount;
+use crate::storage::SpendingLimitRecord;
+
+fn unauthorized() -> ApiError {
+    ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
+}
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("spending limit storage error: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access spending limits")
+}
+
+/// 最小单位 → 整单位十进制
+fn whole_units(network: &str, minimal: u128) -> String {
+    Amount::native(network, minimal).map(|a| a.to_decimal_string()).unwrap_or_else(|_| minimal.to_string())
+}
+
+async fn limits_response(state: &WalletServer, name: &str) -> Result<SpendingLimitsResponse, ApiError> {
+    let records: Vec<SpendingLimitRecord> = state.storage.spending_limits(name).await.map_err(storage_error)?;
+    let mut limits = Vec::with_capacity(records.len());
+    for record in records {
+        let spent = state.storage.sent_in_last_day(name, &record.network).await.map_err(storage_error)?;
+        let network = record.network.as_str();
+        limits.push(SpendingLimitView {
+            max_per_tx: record.max_per_tx.map(|v| whole_units(network, v)),
+            max_per_day: record.max_per_day.map(|v| whole_units(network, v)),
+            spent_last_day: whole_units(network, spent),
+            network: record.network,
+            updated_by: record.updated_by,
+            updated_at: record.updated_at,
+        });
+    }
+    Ok(SpendingLimitsResponse { wallet_name: name.to_string(), limits })
+}
+
+/// `GET /api/wallets/:name/limits`：wallet owner 或 admin
+pub async fn get_spending_limits(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<SpendingLimitsResponse>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    limits_response(&state, name).await.map(Json)
+}
+
+/// `PUT /api/wallets/:name/limits`：仅 admin（API key），避免 owner 自行放宽上限
+///
+/// 每次设置一个network；响应为wallet在所有network上的上限。
+pub async fn put_spending_limits(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<SpendingLimitUpdate>,
+) -> Result<Json<SpendingLimitsResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let name = name.as_str();
+    let network = payload.network.as_str();
+    state
+        .storage
+        .set_spending_limit(name, network, payload.max_per_tx, payload.max_per_day, ADMIN_ISSUER)
+        .await
+        .map_err(storage_error)?;
+
+    let details = serde_json::json!({
+        "network": network,
+        "max_per_tx": payload.max_per_tx.map(|v| v.to_string()),
+        "max_per_day": payload.max_per_day.map(|v| v.to_string()),
+        "updated_by": ADMIN_ISSUER,
+    });
+    if let Err(e) = state.storage.log_action(name, "spending_limits.updated", &details.to_string(), None, None).await
+    {
+        error!("failed to audit spending limit change on {}: {}", name, e);
+    }
+    limits_response(&state, name).await.map(Json)
+}
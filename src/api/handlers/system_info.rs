@@ -1,5 +1,16 @@
-use axum::{http::StatusCode, response::Json};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
 use serde::Serialize;
+use std::sync::Arc;
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::server::WalletServer;
+use crate::api::types::{ApiError, VersionResponse};
+use crate::build_info::{BuildInfo, RuntimeInfo};
 
 /// 系统信息响应
 #[derive(Debug, Serialize)]
@@ -34,18 +45,43 @@ pub async fn system_info() -> (StatusCode, Json<SystemInfoResponse>) {
     (StatusCode::OK, Json(info))
 }
 
+/// GET /api/version
+///
+/// 构建信息与运行时信息；API key 或有效会话 token 均可读取
+pub async fn version(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+) -> Result<Json<VersionResponse>, ApiError> {
+    let session = match crate::api::middleware::extract_user::extract_token(&headers) {
+        Some(token) => state.session_store.validate_token(&token).await.is_ok(),
+        None => false,
+    };
+    if !session && authenticate(&headers, &state.api_key).await.is_err() {
+        return Err(ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized"));
+    }
+    Ok(Json(collect_version(&state).await))
+}
+
+/// 当前构建与运行时信息（版本端点、admin summary 与启动日志共用）
+pub async fn collect_version(state: &WalletServer) -> VersionResponse {
+    let schema_version = match state.storage.schema_version().await {
+        Ok(version) => Some(version),
+        Err(e) => {
+            tracing::warn!("schema version query failed: {}", e);
+            None
+        }
+    };
+    VersionResponse { build: BuildInfo::current(), runtime: RuntimeInfo::collect(&state.config, schema_version) }
+}
+
 /// fetchRust编译器版本
 fn rustc_version() -> String {
-    option_env!("RUSTC_VERSION")
-        .unwrap_or(env!("CARGO_PKG_RUST_VERSION"))
-        .to_string()
+    env!("BUILD_RUSTC_VERSION").to_string()
 }
 
 /// fetch构建时间
 fn build_time() -> String {
-    option_env!("BUILD_TIME")
-        .unwrap_or("unknown")
-        .to_string()
+    BuildInfo::current().build_timestamp
 }
 
 /// fetch构建配置（debug/release）
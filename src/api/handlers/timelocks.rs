@@ -0,0 +1,195 @@
+//! 时间锁transaction handlers
+//!
+//! wallet owner 用Password创建：transaction立即sign，nonce 取自保留的 future
+//! nonce 段（见 `storage::nonce_lanes`），密封后只有服务端份额与托管方份额
+//! 合在一起才能打开。托管方份额只在创建响应里出现一次。
+//!
+//! 释放时要求解锁条件已满足、出示托管方份额，并按当前链状态重新validate
+//! （chain id、nonce、gas 下限），通过后才广播。
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use ethers::types::{Address, Bytes, U256};
+use std::sync::Arc;
+use tracing::{error, warn};
+
+use super::approvals::approval_error;
+use super::deadman::unlock_error;
+use super::key_usage::authorize_signing;
+use super::transaction::{guard_recipient, screen_send, send_conflict, send_failed};
+use super::wallet_networks::check_network_allowed;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::{
+    authorize_owner_or_admin, extract_user_id_from_token, verify_wallet_ownership,
+};
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ValidPath, WalletNameParam};
+use crate::intents::{DecisionContext, IntentError};
+use crate::storage::TimelockRecord;
+use crate::timelocks::{CreatedTimelock, TimelockError, TimelockRequest, UnlockConditions};
+
+fn timelock_error(e: TimelockError) -> ApiError {
+    match e {
+        TimelockError::Intent(e @ (IntentError::NonceInUse { .. } | IntentError::NonceLaneReserved { .. })) => {
+            send_conflict(e)
+        }
+        TimelockError::Intent(e) => send_failed(&e),
+        TimelockError::Storage(inner) => {
+            error!("time lock storage failed: {}", inner);
+            note_storage_error(&inner);
+            ApiError::new(ApiErrorCode::DbError, "Failed to access time locks")
+        }
+        e => {
+            let status = match &e {
+                TimelockError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+                TimelockError::NotFound(_) => StatusCode::NOT_FOUND,
+                TimelockError::InvalidShare(_) => StatusCode::FORBIDDEN,
+                TimelockError::Expired { .. } => StatusCode::GONE,
+                TimelockError::Chain(inner) => {
+                    warn!("time lock chain query failed: {}", inner);
+                    StatusCode::BAD_GATEWAY
+                }
+                _ => StatusCode::CONFLICT,
+            };
+            ApiError::from_code(status, e.code(), e.to_string())
+        }
+    }
+}
+
+async fn audit(state: &WalletServer, name: &str, action: &str, details: serde_json::Value) {
+    if let Err(e) = state.storage.log_action(name, action, &details.to_string(), None, None).await {
+        error!("failed to audit {} on {}: {}", action, name, e);
+    }
+}
+
+/// `POST /api/wallets/:name/timelocks`：仅wallet owner（会话 token），需要Password
+///
+/// 超过审批阈值的金额返回 403 `TIMELOCK_REVIEW_REQUIRED`：密封的transaction
+/// 无法再挂起等待审批。
+pub async fn create_timelock(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    Json(req): Json<CreateTimelockRequest>,
+) -> Result<Response, ApiError> {
+    let name = name.as_str();
+    let user_id = extract_user_id_from_token(&headers, &state).await?;
+    verify_wallet_ownership(&user_id, name, &state).await?;
+    let network = req.network.as_str();
+    check_network_allowed(&state, name, network)?;
+
+    let to: Address =
+        req.to.parse().map_err(|_| ApiError::new(
+            ApiErrorCode::InvalidAddress,
+            format!("Invalid recipient address: {}", req.to),
+        ))?;
+    let value = U256::from_dec_str(&req.value)
+        .map_err(|_| ApiError::new(ApiErrorCode::InvalidAmount, format!("Invalid value: {}", req.value)))?;
+    let data = match req.data.as_deref() {
+        Some(data) => Some(Bytes::from(
+            hex::decode(data.trim_start_matches("0x"))
+                .map_err(|_| ApiError::new(ApiErrorCode::InvalidData, "data must be 0x-prefixed hex"))?,
+        )),
+        None => None,
+    };
+    if data.is_none() {
+        if let Err(refused) = guard_recipient(&state, network, to, req.acknowledge_contract_recipient).await {
+            return Ok(refused);
+        }
+    }
+    let amount = ethers::utils::format_ether(value);
+    if state.approvals.needs_review(name, &amount).await.map_err(approval_error)? {
+        return Err(ApiError::new(
+            ApiErrorCode::TimelockReviewRequired,
+            "Amount exceeds the wallet's review threshold; time-locked transactions cannot be held for \
+                    approval"
+                .to_string(),
+        ));
+    }
+
+    let signer = state.wallet_manager.ethereum_signer(name, &req.password).await.map_err(|e| unlock_error(name, e))?;
+    // 释放时只广播这里sign好的transaction，异常检测只能在sign前做
+    screen_send(&state, name, network, to, value, &mut DecisionContext::new()).await?;
+    authorize_signing(&state, name).await?;
+    let request = TimelockRequest {
+        network,
+        to,
+        value,
+        data,
+        conditions: UnlockConditions { unlock_at: req.unlock_at, unlock_block: req.unlock_block },
+        expires_at: req.expires_at,
+    };
+    let created: CreatedTimelock =
+        state.timelocks.create(&state.signing_intents, name, &signer, &request).await.map_err(timelock_error)?;
+    let lock = &created.timelock;
+    audit(
+        &state,
+        name,
+        "timelock.created",
+        serde_json::json!({
+            "timelock_id": lock.id,
+            "network": lock.network,
+            "to": lock.to_address,
+            "value": lock.value,
+            "nonce": lock.nonce,
+            "unlock_at": lock.unlock_at,
+            "unlock_block": lock.unlock_block,
+            "expires_at": lock.expires_at,
+            "created_by": user_id,
+        }),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(created)).into_response())
+}
+
+/// `GET /api/wallets/:name/timelocks`：wallet owner 或 admin
+pub async fn list_timelocks(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<TimelockListResponse>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    let timelocks = state.timelocks.list(name).await.map_err(timelock_error)?;
+    Ok(Json(TimelockListResponse { timelocks }))
+}
+
+/// `POST /api/timelocks/:id/release`：wallet owner 或 admin，带托管方份额
+///
+/// nonce 已被其他transaction用掉时时间锁作废（409 `TIMELOCK_NONCE_CONSUMED`）；
+/// 其余拒绝都保持锁定，条件满足后可以重试。
+pub async fn release_timelock(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<ReleaseTimelockRequest>,
+) -> Result<Json<TimelockRecord>, ApiError> {
+    let lock = state.timelocks.get(&id).await.map_err(timelock_error)?;
+    let name = lock.wallet_name.as_str();
+    let user_id = authorize_owner_or_admin(&headers, &state, name).await?;
+    let released = state
+        .timelocks
+        .release(&state.signing_intents, state.gas_oracle.as_ref(), &id, &req.custodian_share)
+        .await
+        .map_err(|e| {
+            warn!("release of time lock {} refused: {}", id, e);
+            timelock_error(e)
+        })?;
+    audit(
+        &state,
+        name,
+        "timelock.released",
+        serde_json::json!({
+            "timelock_id": id,
+            "tx_hash": released.tx_hash,
+            "released_by": user_id.as_deref().unwrap_or(super::wallet_tokens::ADMIN_ISSUER),
+        }),
+    )
+    .await;
+    Ok(Json(released))
+}
@@ -0,0 +1,100 @@
+//! 非标准 ERC-20 token 行为 handlers（API key）
+//!
+//! 这里设置的行为优先于配置文件 `tokens.behaviors`，删除后回落到配置。发送、
+//! approve 与 bundle 步骤每次都读取最新设置，修改立即对所有实例生效。
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::admin::unauthorized;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::storage::TokenBehaviorRecord;
+use crate::token_registry::{normalize_token, TokenRegistryError};
+
+/// token 设置的审计记录挂在这个 wallet_id 下
+const AUDIT_ID: &str = "system";
+
+fn registry_error(e: TokenRegistryError) -> ApiError {
+    match e {
+        TokenRegistryError::InvalidAddress(_) => ApiError::new(ApiErrorCode::InvalidTokenAddress, e.to_string()),
+        TokenRegistryError::Storage(inner) => {
+            error!("token registry storage error: {}", inner);
+            note_storage_error(&inner);
+            ApiError::new(ApiErrorCode::DbError, "Failed to access token behaviors")
+        }
+    }
+}
+
+fn known_network(state: &WalletServer, network: &str) -> Result<(), ApiError> {
+    if state.config.blockchain.networks.contains_key(network) {
+        return Ok(());
+    }
+    Err(ApiError::new(ApiErrorCode::UnknownNetwork, format!("Unknown network: {}", network)))
+}
+
+async fn audit(state: &WalletServer, action: &str, details: serde_json::Value) {
+    if let Err(e) = state.storage.log_action(AUDIT_ID, action, &details.to_string(), None, None).await {
+        error!("failed to audit {}: {}", action, e);
+    }
+}
+
+/// `GET /api/admin/tokens`：配置与数据库中的 token 行为，以及发送时实际使用的那个
+pub async fn list_tokens(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+) -> Result<Json<TokensResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let tokens = state.tokens.list().await.map_err(registry_error)?;
+    Ok(Json(TokensResponse { tokens }))
+}
+
+/// `PUT /api/admin/tokens/:network/:address`：整体替换该 token 的行为
+pub async fn put_token_behavior(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((network, address)): Path<(String, String)>,
+    Json(req): Json<TokenBehaviorRequest>,
+) -> Result<Json<TokenBehaviorRecord>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    known_network(&state, &network)?;
+    let updated_by = req.updated_by.as_deref().map(str::trim).filter(|u| !u.is_empty()).unwrap_or("admin");
+
+    let record = state.tokens.set(&network, &address, &req.behavior, updated_by).await.map_err(registry_error)?;
+    info!("token behavior of {} on {} set by {}: {:?}", record.token, network, updated_by, req.behavior);
+    let details = serde_json::json!({
+        "network": network,
+        "token": record.token,
+        "behavior": req.behavior,
+        "updated_by": updated_by,
+    });
+    audit(&state, "token_behavior.updated", details).await;
+    Ok(Json(record))
+}
+
+/// `DELETE /api/admin/tokens/:network/:address`：删除设置，回落到配置文件；没有设置时 404
+pub async fn clear_token_behavior(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((network, address)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    known_network(&state, &network)?;
+    let token = normalize_token(&address).map_err(registry_error)?;
+
+    if !state.tokens.clear(&network, &token).await.map_err(registry_error)? {
+        let error = format!("No token behavior set for {} on {}", token, network);
+        return Err(ApiError::new(ApiErrorCode::TokenBehaviorNotFound, error));
+    }
+    info!("token behavior of {} on {} cleared", token, network);
+    audit(&state, "token_behavior.cleared", serde_json::json!({ "network": network, "token": token })).await;
+    Ok(StatusCode::NO_CONTENT)
+}
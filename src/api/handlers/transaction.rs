@@ -1,102 +1,128 @@
 //! transaction相关handlers
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::State,
     http::{HeaderMap, StatusCode},
-    response::Json,
+    response::{IntoResponse, Json, Response},
 };
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, U256};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::api::handlers::approvals::approval_error;
+use crate::api::handlers::deadman::unlock_error;
+use crate::api::handlers::delegations::send_delegated;
+use crate::api::handlers::fiat::{pricing_for, value_of, Pricing};
+use crate::api::handlers::key_usage::authorize_signing;
+use crate::api::handlers::payment_uri::network_for_chain;
+use crate::api::handlers::wallet_networks::check_network_allowed;
+use crate::api::handlers::wallet_tokens::ADMIN_ISSUER;
+use crate::api::error_catalog::ApiErrorCode;
 use crate::api::middleware::authenticate;
-use crate::api::middleware::extract_user::{extract_user_id_from_token, verify_wallet_ownership};
+use crate::api::middleware::wallet_scope::{
+    extract_delegation, extract_wallet_caller, extract_wallet_token, WalletCaller,
+};
+use crate::api::pagination::{PageCursor, PageDirection};
 use crate::api::server::WalletServer;
 use crate::api::types::*;
-use crate::core::validation::{validate_address, validate_amount};
-use crate::api::validators::{validate_wallet_name, validate_transaction_amount, validate_wallet_address};
+use crate::anomaly_detection::{ThreatLevel, WalletContext};
+use crate::api::validators::{
+    Amount, EvmAddress, NetworkName, ParamError, TxHash, Validate, ValidJson, ValidPath, ValidQuery,
+    WalletNameParam,
+};
+use crate::approvals::HoldRequest;
+use crate::blockchain::erc20::decode_transfer;
+use crate::blockchain::ethereum::raw_tx::{verify_raw_transaction, DecodedRawTransaction, RawTxError, RawTxPolicy};
+use crate::blockchain::recipient_guard::{plain_transfer_recipient, RecipientClass};
+use crate::blockchain::traits::EstimateError;
+use crate::core::errors::WalletError;
+use crate::intents::{DecisionContext, IntentError, SendFingerprint};
+use crate::pricing::native_symbol;
+use crate::storage::{ApprovalPayload, ApprovalRecord, WalletCapability};
+
+/// Network 参数：优先使用 query，没有则用 body（兼容两种方式）；都没有时按
+/// `payment_uri` 的 chain id 解析。显式给出的network必须与链接的 chain id 一致
+fn send_network(
+    state: &WalletServer,
+    query: Option<NetworkName>,
+    payload: &SendTransaction,
+) -> Result<NetworkName, ParamError> {
+    let explicit = query.map(NetworkName::require_evm).transpose()?.or(payload.network);
+    match (explicit, payload.payment_chain_id) {
+        (Some(network), Some(chain_id)) => {
+            if state.config.blockchain.networks.get(network.as_str()).map(|n| n.chain_id) != Some(chain_id) {
+                return Err(ParamError::PaymentUriMismatch("network"));
+            }
+            Ok(network)
+        }
+        (Some(network), None) => Ok(network),
+        (None, Some(chain_id)) => network_for_chain(state, chain_id),
+        (None, None) => Err(ParamError::NetworkMissing),
+    }
+}
 
 pub async fn send_transaction(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
-    Path(name): Path<String>,
-    Query(query_params): Query<std::collections::HashMap<String, String>>,
-    Json(payload): Json<SendTransactionRequest>,
-) -> Result<Json<TransactionResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // ✅ 使用新的user认证机制
-    let user_id = extract_user_id_from_token(&headers, &state).await?;
-    
-    // ✅ validatewallet属于该user（权限check）
-    verify_wallet_ownership(&user_id, &name, &state).await?;
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(query): ValidQuery<OptionalNetworkQuery>,
+    ValidJson(payload): ValidJson<SendTransaction>,
+) -> Result<Response, ApiError> {
+    let name = name.as_str();
 
-    // validateWallet name（使用共享validate器）
-    validate_wallet_name(&name)?;
-    
-    // ✅ validatetransaction金额
-    validate_transaction_amount(&payload.amount)?;
+    // 委托会话 token：无需Password，按委托的预算与限制发送
+    if let Some((delegation, token)) = extract_delegation(&headers, &state).await? {
+        if payload.dry_run {
+            return Err(ParamError::DryRun("is not available to delegation sessions").into());
+        }
+        let network = send_network(&state, query.network, &payload)?;
+        return send_delegated(&state, delegation, &token, name, network.as_str(), &payload).await;
+    }
+
+    // ✅ 使用新的user认证机制（会话 token 或wallet范围 token）
+    let caller = extract_wallet_caller(&headers, &state).await?;
     
-    // ✅ validate目标address格式
-    validate_wallet_address(&payload.to)?;
+    // ✅ validatewallet属于该user（权限check）
+    caller.authorize(&state, name, WalletCapability::Send).await?;
+    // 超出上限而被拒绝的请求同样记为一次 token 使用
+    caller.audit(&state, name, "send").await;
+    // token 的单笔上限独立于wallet自身限额
+    caller.check_amount(&payload.amount)?;
 
-    // ✅ 非托管模式：wallet存在性已由verify_wallet_ownershipvalidate（第29行）
+    // ✅ 非托管模式：wallet存在性已由verify_wallet_ownershipvalidate
     // 不再需要checkwallet_manager，因为非托管wallet不在那里
     
-    // Network 参数：优先使用 query，没有则用 body（兼容两种方式）
-    let network = query_params.get("network")
-        .map(|s| s.as_str())
-        .unwrap_or(&payload.network);
-
-    // Validate required parameters after wallet exists
-    if payload.to.is_empty() || payload.amount.is_empty() || network.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Missing required parameters".to_string(),
-                code: "TRANSACTION_FAILED".to_string(),
-            }),
-        ));
-    }
-
-    // Validate amount
-    if let Err(_e) = validate_amount(&payload.amount) {
-        // ✅ 不泄漏validate细节
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid amount format".to_string(),
-                code: "TRANSACTION_FAILED".to_string(),
-            }),
-        ));
-    }
-
-    // Validate address format based on network
-    if let Err(_e) = validate_address(&payload.to, network) {
-        // ✅ 不泄漏validate细节
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid address format".to_string(),
-                code: "TRANSACTION_FAILED".to_string(),
-            }),
-        ));
-    }
-
-    // Validate network support（支持前端约定的 canonical 值）
-    if !matches!(
-        network,
-        "ethereum" | "bitcoin" | "polygon" | "bsc" |
-        "eth" | "sepolia" | "bsctestnet" | "polygon-testnet"
-    ) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Unsupported network".to_string(),
-                code: "TRANSACTION_FAILED".to_string(),
-            }),
-        ));
-    }
+    let network = send_network(&state, query.network, &payload)?;
+    let network = network.as_str();
+    check_network_allowed(&state, name, network)?;
 
     // ✅ 非托管模式：check是否提供了已Sign transaction
     if let Some(signed_tx) = &payload.signed_tx {
+        // 外部sign的transaction：chain id 必须与目标网络一致，且不得占用我们已sign的 nonce
+        let decoded = verify_external_transaction(&state, network, signed_tx, payload.allow_unprotected).await?;
+        // 已sign的transaction无法挂起等待审批；按transaction实际转出的金额判断，不信 `amount`
+        let moved = signed_amount(&state, network, &decoded);
+        let review = state.approvals.review(name, moved.as_deref().unwrap_or("0")).await.map_err(approval_error)?;
+        // 未登记 token 的数量无法换算：设了阈值就需审批
+        if review.required || (moved.is_none() && review.threshold.is_some()) {
+            return Err(ApiError::new(
+                ApiErrorCode::ReviewRequiresServerSigning,
+                "Amount exceeds the wallet's review threshold; send it server-signed so it can be approved",
+            ));
+        }
+        // 原生币或 ERC-20 transfer 才检查收款方；其他合约调用本来就以合约为目标
+        if let Some(recipient) = plain_transfer_recipient(decoded.to, &decoded.data) {
+            if let Err(refused) =
+                guard_recipient(&state, network, recipient, payload.acknowledge_contract_recipient).await
+            {
+                return Ok(refused);
+            }
+        }
+
         // 非托管模式：广播已Sign transaction
         tracing::info!("✅ 非托管模式：广播已Sign transaction, wallet={}, network={}", name, network);
         
@@ -104,14 +130,7 @@ pub async fn send_transaction(
             .map_err(|e| {
                 tracing::error!("广播transactionfailed: {}", e);
                 use crate::security::error_sanitizer::sanitize_error_message;
-                let safe_msg = sanitize_error_message(&e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: safe_msg,
-                        code: "BROADCAST_FAILED".to_string(),
-                    }),
-                )
+                ApiError::new(ApiErrorCode::BroadcastFailed, sanitize_error_message(&e))
             })?;
         
         return Ok(Json(TransactionResponse { 
@@ -122,47 +141,651 @@ pub async fn send_transaction(
             timestamp: chrono::Utc::now().to_rfc3339(),
             fee: "0.0".to_string(),
             confirmations: "0".to_string(),
-        }));
+            explorer_url: state.config.blockchain.explorer_tx_url(network, &tx_hash),
+        })
+        .into_response());
     }
     
     // 托管模式（兼容旧版）：需要password
     let password = payload.password.as_ref()
         .ok_or_else(|| {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: "非托管模式需要提供signed_tx，托管模式需要提供password".to_string(),
-                    code: "MISSING_PARAMETER".to_string(),
-                }),
-            )
+            ApiError::new(ApiErrorCode::MissingParameter, "非托管模式需要提供signed_tx，托管模式需要提供password")
         })?;
 
-    match state
-        .wallet_manager
-        .send_transaction(&name, &payload.to, &payload.amount, network, password)
+    let to: Address = payload.to.as_str().parse().map_err(|e| send_failed(&e))?;
+    let recipient = match guard_recipient(&state, network, to, payload.acknowledge_contract_recipient).await {
+        Ok(class) => class,
+        Err(refused) => return Ok(refused),
+    };
+
+    let mut decision = DecisionContext::new();
+    caller.record_token_limit(&mut decision);
+    decision.record_recipient(recipient);
+    if payload.dry_run {
+        return dry_run_send(&state, name, &payload, network, password, decision).await;
+    }
+    let requester = Requester { principal: caller.user_id(), token_id: caller.token_id() };
+    let tx_hash = match send_signed_by_server(
+        &state,
+        requester,
+        name,
+        &payload.to,
+        &payload.amount,
+        network,
+        password,
+        payload.allow_duplicate,
+        decision,
+    )
+    .await?
+    {
+        ServerSend::Sent(tx_hash) => tx_hash,
+        ServerSend::Held(approval) => return Ok(held_response(&approval)),
+    };
+    Ok(Json(TransactionResponse {
+        tx_id: tx_hash.clone(),
+        explorer_url: state.config.blockchain.explorer_tx_url(network, &tx_hash),
+        tx_hash: Some(tx_hash),
+        status: "sent".to_string(),
+        network: network.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        fee: "0.0".to_string(),
+        confirmations: "0".to_string(),
+    })
+    .into_response())
+}
+
+/// 已sign transaction转出的金额（十进制，18 位小数）：原生币的 `value` 与 ERC-20
+/// `transfer` 的数量取较大者，token 数量按注册表里的小数位换算。transfer 的
+/// token 不在注册表里时返回 `None`
+fn signed_amount(state: &WalletServer, network: &str, decoded: &DecodedRawTransaction) -> Option<String> {
+    let mut moved = decoded.value;
+    if let (Some(contract), Some((_, amount))) = (decoded.to, decode_transfer(&decoded.data)) {
+        let decimals = state.tokens.resolve(network, &format!("{:?}", contract))?.decimals;
+        let scaled = if decimals <= 18 {
+            amount.checked_mul(U256::exp10(18 - decimals as usize))?
+        } else {
+            amount / U256::exp10(decimals as usize - 18)
+        };
+        moved = moved.max(scaled);
+    }
+    Some(ethers::utils::format_ether(moved))
+}
+
+/// `dry_run`：做与服务端sign发送相同的check，再经节点估算 gas、gas price 与 nonce
+///
+/// 不sign、不广播，也不预留 nonce 或计入密钥使用量；`nonce_to_use` 是节点上含
+/// pending 的transaction数，即现在发送时所用的 nonce。
+async fn dry_run_send(
+    state: &WalletServer,
+    wallet_name: &str,
+    payload: &SendTransaction,
+    network: &str,
+    password: &str,
+    mut decision: DecisionContext,
+) -> Result<Response, ApiError> {
+    let to: Address = payload.to.as_str().parse().map_err(|e| send_failed(&e))?;
+    let value = ethers::utils::parse_ether(payload.amount.as_str()).map_err(|e| send_failed(&e))?;
+    check_duplicate(state, wallet_name, network, to, value, payload.allow_duplicate, &mut decision).await?;
+    check_spending_limit(state, wallet_name, network, value, 0, &mut decision).await?;
+    // 解密wallet同时validatePassword；只用它的address
+    let signer = state.wallet_manager.ethereum_signer(wallet_name, password).await.map_err(|e| send_failed(&e))?;
+    let from = format!("{:#x}", signer.address());
+
+    let client =
+        state.chain_clients.get(network).map_err(|e| ApiError::new(ApiErrorCode::NetworkUnavailable, e.to_string()))?;
+    let estimate = client.estimate_transfer(&from, payload.to.as_str(), payload.amount.as_str()).await.map_err(|e| {
+        tracing::warn!("dry run from {} on {} failed: {}", wallet_name, network, e);
+        let code = match &e {
+            EstimateError::Reverted(_) => ApiErrorCode::GasEstimationReverted,
+            EstimateError::Rpc(WalletError::NetworkBusy(_)) => ApiErrorCode::NetworkBusy,
+            EstimateError::Rpc(_) => ApiErrorCode::GasEstimationFailed,
+        };
+        ApiError::new(code, e.to_string())
+    })?;
+
+    let fee = estimate.total_fee();
+    Ok(Json(SendDryRunResponse {
+        dry_run: true,
+        network: network.to_string(),
+        from_address: from,
+        to: payload.to.as_str().to_string(),
+        amount: payload.amount.as_str().to_string(),
+        estimated_gas: estimate.gas.to_string(),
+        gas_price: estimate.gas_price.to_string(),
+        total_fee_wei: fee.to_string(),
+        total_cost_wei: fee.saturating_add(value).to_string(),
+        nonce_to_use: estimate.nonce,
+    })
+    .into_response())
+}
+
+fn unauthorized() -> ApiError {
+    ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized")
+}
+
+/// Who asked for a server-signed send; kept with held sends for re-validation
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Requester<'a> {
+    /// User id, or `admin` for API-key callers
+    pub principal: &'a str,
+    pub token_id: Option<&'a str>,
+}
+
+/// 服务端sign发送的结果
+pub(crate) enum ServerSend {
+    Sent(String),
+    /// 超过wallet审查阈值，等待审批
+    Held(Box<ApprovalRecord>),
+}
+
+fn held_response(approval: &ApprovalRecord) -> Response {
+    (
+        StatusCode::ACCEPTED,
+        Json(PendingApprovalResponse {
+            approval_id: approval.id.clone(),
+            status: "pending_approval".to_string(),
+            expires_at: approval.expires_at,
+        }),
+    )
+        .into_response()
+}
+
+/// `POST /api/wallets/:name/send_batch`：按顺序服务端sign发送一组转账
+///
+/// 每笔先做与 `/send` 相同的check（收款方、重复发送）；合计金额按 token 上限与
+/// 审查阈值check，超过阈值的批次直接拒绝（403 `BATCH_REQUIRES_REVIEW`）：挂起的
+/// 一笔会卡住其后所有 nonce。通过check的各笔一次预留连续的 nonce 区间，再依次
+/// sign广播。
+///
+/// check失败的一笔记为 `failed`，其余照常发送；设置 `fail_fast` 时整批不再发送。
+/// 已分配 nonce 的一笔sign或广播失败后，后面的 nonce 无法上链，因此无论是否
+/// `fail_fast` 都停止，未用的 nonce 归还。
+pub async fn send_batch(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(query): ValidQuery<OptionalNetworkQuery>,
+    ValidJson(batch): ValidJson<SendBatch>,
+) -> Result<Json<SendBatchResponse>, ApiError> {
+    let name = name.as_str();
+    let caller = extract_wallet_caller(&headers, &state).await?;
+    caller.authorize(&state, name, WalletCapability::Send).await?;
+    caller.audit(&state, name, "send_batch").await;
+
+    // 连续的 nonce 只对同一network有意义
+    let network = send_network(&state, query.network, &batch.transactions[0])?;
+    for (index, item) in batch.transactions.iter().enumerate().skip(1) {
+        if send_network(&state, query.network, item)? != network {
+            let message = format!("transactions[{}]: every transaction must be on {}", index, network.as_str());
+            return Err(ParamError::Batch(message).into());
+        }
+    }
+    let network = network.as_str();
+    let allowed = check_network_allowed(&state, name, network)?;
+
+    // 拆成多笔绕不过 token 单笔上限与审查阈值
+    let total = batch
+        .transactions
+        .iter()
+        .try_fold(Decimal::ZERO, |total, item| total.checked_add(item.amount.as_str().parse().ok()?))
+        .ok_or(ParamError::AmountTooLong)?;
+    let total = Amount::try_from(total.normalize().to_string().as_str())?;
+    caller.check_amount(&total)?;
+    let review = state.approvals.review(name, total.as_str()).await.map_err(approval_error)?;
+    if review.required {
+        return Err(ApiError::new(
+            ApiErrorCode::BatchRequiresReview,
+            format!(
+                "Batch sends {} on {} in total, above the wallet's review threshold; batches cannot be held for \
+                 approval",
+                total, network
+            ),
+        ));
+    }
+
+    let mut base = DecisionContext::new();
+    base.record_requester(caller.user_id());
+    caller.record_token_limit(&mut base);
+    base.record_network(network, allowed);
+    base.record_review(review);
+    let signer =
+        state.wallet_manager.ethereum_signer(name, &batch.password).await.map_err(|e| unlock_error(name, e))?;
+
+    // 成功的发送由sign意图日志计入 transactions_sent，这里只计失败
+    let metrics = &state.metrics;
+    let mut results: Vec<BatchItemResult> =
+        (0..batch.transactions.len()).map(|index| BatchItemResult { index, ..Default::default() }).collect();
+    // 整批按一次发送持有，各笔的额度check与记录之间不插入其他发送
+    let _sends = state.storage.lock_sends(name, network).await;
+    let mut planned: Vec<(usize, Address, U256, DecisionContext)> = Vec::new();
+    for (index, item) in batch.transactions.iter().enumerate() {
+        match check_batch_item(&state, name, network, index, item, &planned, &base).await {
+            Ok((to, value, decision)) => planned.push((index, to, value, decision)),
+            Err(e) => {
+                metrics.record_transaction_failed();
+                results[index].fail(e);
+                if batch.fail_fast {
+                    planned.clear();
+                    let reason = format!("Not sent: transactions[{}] failed and fail_fast is set", index);
+                    results.iter_mut().filter(|r| r.status.is_empty()).for_each(|r| r.skip(&reason));
+                    break;
+                }
+            }
+        }
+    }
+
+    if !planned.is_empty() {
+        let from = signer.address();
+        let address = format!("{:#x}", from);
+        let floor =
+            state.signing_intents.chain().transaction_count(network, from).await.map_err(|e| send_failed(&e))?;
+        let count = planned.len() as u64;
+        let start =
+            state.storage.reserve_nonce_range(network, &address, floor, count).await.map_err(|e| send_failed(&e))?;
+        let mut next = start;
+        let mut stopped_at = None;
+        for (index, to, value, decision) in planned {
+            if let Some(failed) = stopped_at {
+                results[index].skip(&format!("Not sent: transactions[{}] failed after its nonce was assigned", failed));
+                continue;
+            }
+            let tx = ethers::types::TransactionRequest::new().to(to).value(value).nonce(next).into();
+            let sent = match authorize_signing(&state, name).await {
+                Ok(()) => send_through_intents(&state, name, network, &signer, tx, Some(decision)).await,
+                Err(e) => Err(e),
+            };
+            results[index].nonce = Some(next);
+            match sent {
+                Ok(tx_hash) => {
+                    results[index].status = "sent".to_string();
+                    results[index].tx_hash = Some(tx_hash);
+                    next += 1;
+                }
+                Err(e) => {
+                    metrics.record_transaction_failed();
+                    results[index].fail(e);
+                    stopped_at = Some(index);
+                }
+            }
+        }
+        // 未广播的 nonce 归还；已有更晚的预留时留空，下次预留仍从链上 pending 数起
+        if next < start + count {
+            match state.storage.release_nonce_range(network, &address, next, start + count).await {
+                Ok(true) => {}
+                Ok(false) => tracing::warn!("nonces [{}, {}) of {} stay reserved", next, start + count, address),
+                Err(e) => tracing::error!("failed to release nonces of {} on {}: {}", address, network, e),
+            }
+        }
+    }
+
+    let first_failure = results.iter().find(|r| r.status == "failed").map(|r| r.index);
+    tracing::info!(
+        "batch of {} on {} from {}: {} sent",
+        results.len(),
+        network,
+        name,
+        results.iter().filter(|r| r.status == "sent").count()
+    );
+    Ok(Json(SendBatchResponse { network: network.to_string(), results, first_failure }))
+}
+
+/// 批量中一笔的发送前check；与前面已通过的某笔完全相同时按重复发送处理
+async fn check_batch_item(
+    state: &WalletServer,
+    wallet_name: &str,
+    network: &str,
+    index: usize,
+    item: &SendTransaction,
+    planned: &[(usize, Address, U256, DecisionContext)],
+    base: &DecisionContext,
+) -> Result<(Address, U256, DecisionContext), ApiError> {
+    let to: Address = item.to.as_str().parse().map_err(|e| send_failed(&e))?;
+    let value = ethers::utils::parse_ether(item.amount.as_str()).map_err(|e| send_failed(&e))?;
+    let class = state
+        .recipient_guard
+        .check(state.gas_oracle.as_ref(), network, to, item.acknowledge_contract_recipient)
         .await
+        .map_err(|e| ApiError::from_code(StatusCode::UNPROCESSABLE_ENTITY, e.code(), e.to_string()))?;
+    if !item.allow_duplicate {
+        if let Some((earlier, ..)) = planned.iter().find(|(_, t, v, _)| *t == to && *v == value) {
+            return Err(ApiError::new(
+                ApiErrorCode::DuplicateTransactionSuspected,
+                format!(
+                    "transactions[{}] repeats transactions[{}]; set allow_duplicate to send both",
+                    index, earlier
+                ),
+            ));
+        }
+    }
+    let mut decision = base.clone();
+    decision.record_recipient(class);
+    check_duplicate(state, wallet_name, network, to, value, item.allow_duplicate, &mut decision).await?;
+    let planned_total =
+        planned.iter().fold(0u128, |total, (_, _, v, _)| total.saturating_add(u128::try_from(*v).unwrap_or(u128::MAX)));
+    check_spending_limit(state, wallet_name, network, value, planned_total, &mut decision).await?;
+    Ok((to, value, decision))
+}
+
+impl BatchItemResult {
+    fn fail(&mut self, e: ApiError) {
+        self.status = "failed".to_string();
+        self.error = Some(e.message().to_string());
+        self.code = Some(e.code().to_string());
+    }
+
+    fn skip(&mut self, reason: &str) {
+        self.status = "skipped".to_string();
+        self.error = Some(reason.to_string());
+        self.code = Some("BATCH_ABORTED".to_string());
+    }
+}
+
+/// 托管模式发送：sign与广播经由sign意图日志，崩溃后可对账
+///
+/// nonce 被未释放的意图占用时返回 409 `NONCE_IN_USE`；与窗口内未确认的
+/// transaction完全相同（且未设 `allow_duplicate`）时返回 409 `DUPLICATE_TRANSACTION_SUSPECTED`。
+/// 金额超过wallet审查阈值时不sign，挂起为待审批请求。
+///
+/// `decision` 带着调用方已做的check（token 上限、收款方分类），这里补上其余check
+/// 的结果，随transaction记录一起写入。
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn send_signed_by_server(
+    state: &WalletServer,
+    requester: Requester<'_>,
+    wallet_name: &str,
+    to: &EvmAddress,
+    amount: &Amount,
+    network: &str,
+    password: &str,
+    allow_duplicate: bool,
+    mut decision: DecisionContext,
+) -> Result<ServerSend, ApiError> {
+    // 两者均已validate
+    let to: Address = to.as_str().parse().map_err(|e| send_failed(&e))?;
+    let value = ethers::utils::parse_ether(amount.as_str()).map_err(|e| send_failed(&e))?;
+
+    decision.record_requester(requester.principal);
+    let allowed = check_network_allowed(state, wallet_name, network)?;
+    decision.record_network(network, allowed);
+    check_duplicate(state, wallet_name, network, to, value, allow_duplicate, &mut decision).await?;
+    let _sends = state.storage.lock_sends(wallet_name, network).await;
+    check_spending_limit(state, wallet_name, network, value, 0, &mut decision).await?;
+    // 挂起的发送在批准后经 `send_with_signer` 做异常检测
+    let signer = state.wallet_manager.ethereum_signer(wallet_name, password).await.map_err(|e| send_failed(&e))?;
+
+    let review = state.approvals.review(wallet_name, amount.as_str()).await.map_err(approval_error)?;
+    if review.required {
+        let payload = ApprovalPayload {
+            from: format!("{:#x}", signer.address()),
+            to: format!("{:#x}", to),
+            value: value.to_string(),
+            token: None,
+            allow_duplicate,
+        };
+        let hold = HoldRequest {
+            wallet_name,
+            network,
+            amount: amount.as_str(),
+            payload,
+            requested_by: requester.principal,
+            token_id: requester.token_id,
+        };
+        let approval = state.approvals.hold(hold, signer).await.map_err(approval_error)?;
+        return Ok(ServerSend::Held(Box::new(approval)));
+    }
+
+    decision.record_review(review);
+
+    // 服务端sign：计入密钥使用量并执行轮换策略
+    authorize_signing(state, wallet_name).await?;
+    let tx_hash = send_with_signer(state, wallet_name, network, &signer, to, value, Some(decision)).await?;
+    Ok(ServerSend::Sent(tx_hash))
+}
+
+/// 普通转账的收款方检查：收款方是代币合约或未知合约且未确认时返回
+/// 422 `RECIPIENT_IS_CONTRACT`，`details` 带收款方与分类
+pub(crate) async fn guard_recipient(
+    state: &WalletServer,
+    network: &str,
+    recipient: Address,
+    acknowledged: bool,
+) -> Result<RecipientClass, Response> {
+    state.recipient_guard.check(state.gas_oracle.as_ref(), network, recipient, acknowledged).await.map_err(|e| {
+        tracing::warn!("refused unacknowledged transfer on {}: {}", network, e);
+        ApiError::from_code(StatusCode::UNPROCESSABLE_ENTITY, e.code(), e.to_string())
+            .with_details(serde_json::json!({
+                "recipient": ethers::utils::to_checksum(&e.recipient, None),
+                "classification": e.class,
+            }))
+            .into_response()
+    })
+}
+
+/// 500 `TRANSACTION_FAILED`：原始错误只写日志
+pub(crate) fn send_failed(e: &dyn std::fmt::Display) -> ApiError {
+    use crate::security::error_sanitizer::sanitize_for_logging;
+    tracing::error!("send failed: {}", sanitize_for_logging(&e.to_string()));
+    ApiError::new(ApiErrorCode::TransactionFailed, "Failed to send transaction")
+}
+
+pub(crate) fn send_conflict(e: IntentError) -> ApiError {
+    ApiError::from_code(StatusCode::CONFLICT, e.code(), e.to_string())
+}
+
+/// 解码外部sign的原始transaction并执行 [`RawTxPolicy`] 与sign意图 nonce check
+pub(crate) async fn verify_external_transaction(
+    state: &WalletServer,
+    network: &str,
+    raw: &str,
+    allow_unprotected: bool,
+) -> Result<DecodedRawTransaction, ApiError> {
+    let result = match RawTxPolicy::for_network(&state.config.blockchain, network, allow_unprotected) {
+        Ok(policy) => verify_raw_transaction(&state.storage, network, &policy, raw).await,
+        Err(e) => Err(e),
+    };
+    result.map_err(|e| {
+        let status = match e {
+            RawTxError::NonceConflict { .. } => StatusCode::CONFLICT,
+            RawTxError::Storage(_) => {
+                tracing::error!("raw transaction verification failed: {}", e);
+                return ApiError::from_code(StatusCode::INTERNAL_SERVER_ERROR, e.code(), "Failed to verify transaction");
+            }
+            _ => StatusCode::BAD_REQUEST,
+        };
+        tracing::warn!("rejected raw transaction for {}: {}", network, e);
+        ApiError::from_code(status, e.code(), e.to_string())
+    })
+}
+
+/// 通过时把所用的窗口记入 `decision`
+pub(crate) async fn check_duplicate(
+    state: &WalletServer,
+    wallet_name: &str,
+    network: &str,
+    to: Address,
+    value: U256,
+    allow_duplicate: bool,
+    decision: &mut DecisionContext,
+) -> Result<(), ApiError> {
+    let fingerprint = SendFingerprint { wallet_name, network, to, token: None, amount: value };
+    state.signing_intents.check_duplicate(&fingerprint, allow_duplicate).await.map_err(|e| match e {
+        e @ IntentError::DuplicateSuspected { .. } => send_conflict(e),
+        e => send_failed(&e),
+    })?;
+    decision.record_duplicate_check(state.signing_intents.duplicate_window_secs(), allow_duplicate);
+    Ok(())
+}
+
+/// wallet在该network上的支出上限check，全部按最小单位（u128）整数比较
+///
+/// 调用方须先取得 `storage.lock_sends`，并持有到transaction记录写入，否则并发的
+/// 两笔可能都按同一已发送额通过。
+/// `planned` 是同一请求中已通过、尚未记录的金额（批量发送），一并计入当日已发送。
+/// 超出上限返回 403 `SPENDING_LIMIT_EXCEEDED` / `DAILY_SPENDING_LIMIT_EXCEEDED`；
+/// 未设置上限时直接通过。
+pub(crate) async fn check_spending_limit(
+    state: &WalletServer,
+    wallet_name: &str,
+    network: &str,
+    value: U256,
+    planned: u128,
+    decision: &mut DecisionContext,
+) -> Result<(), ApiError> {
+    let storage_error = |e: anyhow::Error| {
+        tracing::error!("spending limit check for {} on {} failed: {}", wallet_name, network, e);
+        ApiError::new(ApiErrorCode::DbError, "Failed to check spending limit")
+    };
+    let Some(limit) = state.storage.spending_limit(wallet_name, network).await.map_err(storage_error)? else {
+        return Ok(());
+    };
+    // 超出 u128 的金额必然超过任何上限
+    let amount = u128::try_from(value).unwrap_or(u128::MAX);
+    let spent = match limit.max_per_day {
+        Some(_) => {
+            let recorded = state.storage.sent_in_last_day(wallet_name, network).await.map_err(storage_error)?;
+            Some(recorded.saturating_add(planned))
+        }
+        None => None,
+    };
+    limit.check(amount, spent.unwrap_or(0)).map_err(|e| {
+        tracing::warn!("refused send from {} on {}: {}", wallet_name, network, e);
+        ApiError::from_code(StatusCode::FORBIDDEN, e.code(), e.to_string())
+    })?;
+    decision.record_spending_limit(limit.max_per_tx, limit.max_per_day, spent);
+    Ok(())
+}
+
+/// sign前的异常检测，上下文取自存储：该wallet在该network上的行为画像，以及收款方
+/// 是否收到过它的已确认发送
+///
+/// 结果为 High 及以上且打开了 `anomaly_detection.block_on_high` 时返回 403 `ANOMALY_BLOCKED`，
+/// `details` 中带分数与等级；否则只把分数写入审计日志（`transaction.anomaly_check`）并记入 `decision`。
+/// `value` 无法换算为该network的原生币金额时拒绝发送，不在缺少金额特征的情况下放行。
+///
+/// 由 [`send_through_intents`] 在sign前调用，调用方此时已解锁sign器：Password错误的
+/// 请求不会留下检测记录。
+pub(crate) async fn screen_send(
+    state: &WalletServer,
+    wallet_name: &str,
+    network: &str,
+    to: Address,
+    value: U256,
+    decision: &mut DecisionContext,
+) -> Result<(), ApiError> {
+    let storage_error = |e: anyhow::Error| {
+        tracing::error!("anomaly check for {} on {} failed: {}", wallet_name, network, e);
+        ApiError::new(ApiErrorCode::DbError, "Failed to screen transaction")
+    };
+    let recipient = format!("{:#x}", to);
+    let profile = state.storage.wallet_profile(wallet_name, network).await.map_err(storage_error)?;
+    let recipient_known =
+        state.storage.is_known_recipient(wallet_name, network, &recipient).await.map_err(storage_error)?;
+    let context = WalletContext {
+        profile: profile.as_ref(),
+        recipient_known,
+        timestamp: chrono::Utc::now().timestamp(),
+        recipient_class: decision.recipient(),
+    };
+    // 检测特征只需近似值；精确金额仍以 `value` 为准
+    let amount = crate::core::amount::Amount::native_u256(network, value)
+        .map_err(|e| ApiError::new(ApiErrorCode::InvalidAmount, e.to_string()))?;
+    let amount = amount.to_f64_lossy();
+
+    let mut detector = state.send_detector.lock().await;
+    let result = detector.detect_for_wallet(context, &recipient, amount, None, false);
+    let rules_hash = detector.rules_hash();
+    let blocked = result.is_anomalous
+        && matches!(result.threat_level, ThreatLevel::High | ThreatLevel::Critical)
+        && detector.config().block_on_high;
+    drop(detector);
+
+    let threat_level = format!("{:?}", result.threat_level);
+    let details = serde_json::json!({
+        "network": network,
+        "to": recipient,
+        "score": result.score,
+        "threat_level": threat_level,
+        "reason": result.reason,
+        "rules_hash": rules_hash,
+        "blocked": blocked,
+    });
+    if let Err(e) =
+        state.storage.log_action(wallet_name, "transaction.anomaly_check", &details.to_string(), None, None).await
     {
-        Ok(tx_hash) => Ok(Json(TransactionResponse { 
-            tx_id: tx_hash.clone(), 
-            tx_hash: Some(tx_hash.clone()), 
-            status: "sent".to_string(),
-            network: network.to_string(),
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            fee: "0.0".to_string(),
-            confirmations: "0".to_string(),
-        })),
-        Err(e) => {
-            use crate::security::error_sanitizer::sanitize_error_message;
-            let safe_msg = sanitize_error_message(&e.to_string());
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: safe_msg,
-                    code: "TRANSACTION_FAILED".to_string(),
-                }),
-            ))
-        },
+        tracing::error!("failed to audit anomaly check for {}: {}", wallet_name, e);
+    }
+    if blocked {
+        tracing::warn!(
+            "refused send from {} on {}: {} (score={:.2})",
+            wallet_name,
+            network,
+            result.reason,
+            result.score
+        );
+        let error = ApiError::new(ApiErrorCode::AnomalyBlocked, "Transaction blocked by anomaly detection")
+            .with_details(serde_json::json!({
+                "score": result.score,
+                "threat_level": threat_level,
+                "reason": result.reason,
+            }));
+        return Err(error);
     }
+    decision.record_anomaly(&result, &rules_hash);
+    Ok(())
+}
+
+/// 已解锁 `signer` 的sign与广播（审批通过的发送也走这里）
+///
+/// `decision` 是本次发送通过的各项check，与transaction记录在同一事务中写入。
+pub(crate) async fn send_with_signer(
+    state: &WalletServer,
+    wallet_name: &str,
+    network: &str,
+    signer: &LocalWallet,
+    to: Address,
+    value: U256,
+    decision: Option<DecisionContext>,
+) -> Result<String, ApiError> {
+    let tx = ethers::types::TransactionRequest::new().to(to).value(value).into();
+    send_through_intents(state, wallet_name, network, signer, tx, decision).await
+}
+
+/// 合约调用（ERC-20 approve、任意 calldata）的sign与广播，同样经由sign意图日志
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn send_call_with_signer(
+    state: &WalletServer,
+    wallet_name: &str,
+    network: &str,
+    signer: &LocalWallet,
+    to: Address,
+    value: U256,
+    data: Bytes,
+    decision: Option<DecisionContext>,
+) -> Result<String, ApiError> {
+    let tx = ethers::types::TransactionRequest::new().to(to).value(value).data(data).into();
+    send_through_intents(state, wallet_name, network, signer, tx, decision).await
+}
+
+/// 服务端sign的共同出口：单笔、批量、审批通过、bundle 与 ERC-20 授权都经由这里，
+/// 异常检测因此对所有路径生效
+async fn send_through_intents(
+    state: &WalletServer,
+    wallet_name: &str,
+    network: &str,
+    signer: &LocalWallet,
+    tx: TypedTransaction,
+    mut decision: Option<DecisionContext>,
+) -> Result<String, ApiError> {
+    if let Some(&to) = tx.to_addr() {
+        let value = tx.value().copied().unwrap_or_default();
+        let mut unrecorded = DecisionContext::new();
+        let screened = decision.as_mut().unwrap_or(&mut unrecorded);
+        screen_send(state, wallet_name, network, to, value, screened).await?;
+    }
+    state.signing_intents.send_with_decision(wallet_name, network, signer, tx, decision).await.map_err(|e| match e {
+        e @ (IntentError::NonceInUse { .. } | IntentError::NonceLaneReserved { .. }) => send_conflict(e),
+        e => send_failed(&e),
+    })
 }
 
 /// 广播已Sign transaction到区块链
@@ -194,64 +817,190 @@ async fn broadcast_signed_transaction(signed_tx: &str, network: &str) -> Result<
     Ok(tx_hash)
 }
 
+/// 分页的wallet历史：本地记录的transaction按 `(created_at, id)` 倒序。链上浏览器结果没有
+/// 稳定的排序键，只出现在不分页的旧响应中
+#[derive(Debug, Clone, Copy)]
+pub struct HistoryPages;
+
+impl PageScope for HistoryPages {
+    const SCOPE: &'static str = "wallet_history";
+    const DIRECTION: PageDirection = PageDirection::Desc;
+    const DEFAULT_LIMIT: usize = 50;
+    const MAX_LIMIT: usize = MAX_GROUP_HISTORY_LIMIT;
+}
+
 pub async fn get_transaction_history(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
-    Path(name): Path<String>,
-) -> Result<Json<TransactionHistoryResponse>, (StatusCode, Json<ErrorResponse>)> {
-    authenticate(&headers, &state.api_key).await.map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized".to_string(),
-                code: "AUTH_FAILED".to_string(),
-            }),
-        )
-    })?;
-
-    // Validate wallet name
-    if name.is_empty() || name.contains(|c: char| !c.is_alphanumeric() && c != '_') {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid wallet name".to_string(),
-                code: "HISTORY_FAILED".to_string(),
-            }),
-        ));
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidQuery(request): ValidQuery<PageRequest<HistoryPages>>,
+) -> Result<Response, ApiError> {
+    let name = name.as_str();
+    // wallet范围 token 可读取其wallet的历史；否则需要 API key
+    let mut owner = None;
+    if let Some(token) = extract_wallet_token(&headers, &state).await? {
+        let caller = WalletCaller::Token(token);
+        caller.authorize(&state, name, WalletCapability::ReadHistory).await?;
+        caller.audit(&state, name, "read_history").await;
+        owner = Some(caller.user_id().to_string());
+    } else {
+        authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
     }
 
     match state.wallet_manager.list_wallets().await {
         Ok(wallets) => {
             if !wallets.iter().any(|w| w.name == name) {
-                return Err((
-                    StatusCode::NOT_FOUND,
-                    Json(ErrorResponse {
-                        error: "Wallet not found".to_string(),
-                        code: "HISTORY_FAILED".to_string(),
-                    }),
-                ));
+                return Err(ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found"));
             }
         }
-        Err(_) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to check wallet".to_string(),
-                    code: "HISTORY_FAILED".to_string(),
-                }),
-            ))
+        Err(e) => {
+            tracing::error!("listing wallets for the history of {} failed: {}", name, e);
+            return Err(ApiError::new(ApiErrorCode::HistoryFailed, "Failed to check wallet"));
+        }
+    }
+
+    let pricing = pricing_for(&state, owner.as_deref()).await;
+    history_response(&state, name, pricing, &request).await
+}
+
+/// 带 `cursor`/`limit` 时返回 [`Page`]；否则保持旧的一次性全量响应
+async fn history_response(
+    state: &WalletServer,
+    name: &str,
+    pricing: Option<Pricing<'_>>,
+    request: &PageRequest<HistoryPages>,
+) -> Result<Response, ApiError> {
+    let failed = |e: anyhow::Error| {
+        tracing::error!("loading the history of {} failed: {}", name, e);
+        ApiError::new(ApiErrorCode::HistoryFailed, "Failed to get history")
+    };
+    if !request.explicit {
+        let history = wallet_history(state, name, pricing.as_ref()).await.map_err(failed)?;
+        return Ok(Json(TransactionHistoryResponse {
+            transactions: history,
+            fiat_currency: pricing.map(|p| p.currency),
+        })
+        .into_response());
+    }
+    let cursor = match &request.cursor {
+        Some(c) => {
+            let (created_at, id) = c.as_time_id().ok_or(ParamError::Cursor)?;
+            Some(crate::storage::TransactionCursor { created_at, id })
+        }
+        None => None,
+    };
+    let wallet_ids: Vec<String> = state.storage.wallet_id(name).await.map_err(failed)?.into_iter().collect();
+    let (records, next) =
+        state.storage.wallet_set_transactions(&wallet_ids, cursor.as_ref(), request.limit).await.map_err(failed)?;
+    let mut prices = HashMap::new();
+    let mut items = Vec::with_capacity(records.len());
+    for record in records {
+        items.push(recorded_entry(&state.config.blockchain, record, pricing.as_ref(), &mut prices).await);
+    }
+    let next = next.map(|c| PageCursor::time_id(PageDirection::Desc, c.created_at, &c.id));
+    Ok(Json(HistoryPageResponse {
+        page: request.page(items, next, None).map_err(failed)?,
+        fiat_currency: pricing.map(|p| p.currency),
+    })
+    .into_response())
+}
+
+/// 链上历史在前，其后是本地记录的发送（按 hash 去重）；本地记录带 network，
+/// 可渲染浏览器链接，并在定价开启时带法币价值
+async fn wallet_history(
+    state: &WalletServer,
+    name: &str,
+    pricing: Option<&Pricing<'_>>,
+) -> anyhow::Result<Vec<HistoryTransaction>> {
+    let blockchain = &state.config.blockchain;
+    // 每个币种只查一次当前价格
+    let mut prices: HashMap<&'static str, Option<Decimal>> = HashMap::new();
+    let mut history: Vec<HistoryTransaction> = state
+        .wallet_manager
+        .get_transaction_history(name)
+        .await?
+        .into_iter()
+        .map(|transaction| HistoryTransaction {
+            transaction,
+            network: None,
+            status: None,
+            links: ExplorerLinks::default(),
+            fiat_value: None,
+            fiat_value_at_time: None,
+            fee_actual: None,
+            block_number: None,
+        })
+        .collect();
+    let Some(wallet_id) = state.storage.wallet_id(name).await? else { return Ok(history) };
+    for record in state.storage.get_wallet_transactions(&wallet_id).await? {
+        if history.iter().any(|h| h.transaction.hash.eq_ignore_ascii_case(&record.tx_hash)) {
+            continue;
         }
+        history.push(recorded_entry(blockchain, record, pricing, &mut prices).await);
     }
+    Ok(history)
+}
+
+/// 本地记录的一笔发送：带 network、状态与浏览器链接；`prices` 缓存每个币种的当前价格
+async fn recorded_entry(
+    blockchain: &crate::core::config::BlockchainConfig,
+    record: crate::storage::TransactionRecord,
+    pricing: Option<&Pricing<'_>>,
+    prices: &mut HashMap<&'static str, Option<Decimal>>,
+) -> HistoryTransaction {
+    let (mut fiat_value, mut fiat_value_at_time) = (None, None);
+    if let (Some(pricing), Some(coin)) = (pricing, native_symbol(&record.network)) {
+        let price = match prices.get(coin) {
+            Some(price) => *price,
+            None => {
+                let price = pricing.price(coin).await;
+                prices.insert(coin, price);
+                price
+            }
+        };
+        fiat_value = value_of(&record.amount, price);
+        fiat_value_at_time = value_of(&record.amount, pricing.price_at(coin, record.created_at).await);
+    }
+    HistoryTransaction {
+        links: ExplorerLinks::render(
+            blockchain,
+            &record.network,
+            &record.tx_hash,
+            Some(&record.from_address),
+            Some(&record.to_address),
+        ),
+        transaction: crate::blockchain::traits::TransactionInfo {
+            hash: record.tx_hash,
+            from: record.from_address,
+            to: record.to_address,
+            amount: record.amount,
+        },
+        network: Some(record.network),
+        status: Some(record.status),
+        fiat_value,
+        fiat_value_at_time,
+        fee_actual: record.fee_actual,
+        block_number: record.block_number,
+    }
+}
+
+/// `?wallet_name=` of `GET /api/transactions/history`
+#[derive(Deserialize)]
+pub struct TransactionsHistoryQuery {
+    pub wallet_name: Option<String>,
+}
+
+/// [`TransactionsHistoryQuery`] validate后
+pub struct TransactionsHistoryParams {
+    pub wallet_name: WalletNameParam,
+}
+
+impl Validate for TransactionsHistoryParams {
+    type Raw = TransactionsHistoryQuery;
 
-    match state.wallet_manager.get_transaction_history(&name).await {
-        Ok(history) => Ok(Json(TransactionHistoryResponse { transactions: history })),
-        Err(_) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "Failed to get history".to_string(),
-                code: "HISTORY_FAILED".to_string(),
-            }),
-        )),
+    fn validate(raw: TransactionsHistoryQuery) -> Result<Self, ParamError> {
+        let name = raw.wallet_name.ok_or(ParamError::Missing("wallet_name"))?;
+        Ok(Self { wallet_name: WalletNameParam::try_from(name.as_str())? })
     }
 }
 
@@ -259,47 +1008,14 @@ pub async fn get_transaction_history(
 pub async fn transactions_history(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
-    Query(params): Query<std::collections::HashMap<String, String>>,
-) -> Result<Json<TransactionHistoryResponse>, (StatusCode, Json<ErrorResponse>)> {
-    authenticate(&headers, &state.api_key).await.map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized".to_string(),
-                code: "AUTH_FAILED".to_string(),
-            }),
-        )
-    })?;
+    ValidQuery(params): ValidQuery<TransactionsHistoryParams>,
+    ValidQuery(request): ValidQuery<PageRequest<HistoryPages>>,
+) -> Result<Response, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+    let wallet_name = params.wallet_name.as_str();
 
-    let wallet_name = params.get("wallet_name")
-        .ok_or_else(|| (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Missing wallet_name query parameter".to_string(),
-                code: "MISSING_PARAMETER".to_string(),
-            }),
-        ))?;
-
-    if wallet_name.is_empty() || wallet_name.contains(|c: char| !c.is_alphanumeric() && c != '_') {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid wallet name".to_string(),
-                code: "INVALID_INPUT".to_string(),
-            }),
-        ));
-    }
-
-    match state.wallet_manager.get_transaction_history(wallet_name).await {
-        Ok(history) => Ok(Json(TransactionHistoryResponse { transactions: history })),
-        Err(_) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "Failed to get history".to_string(),
-                code: "HISTORY_FAILED".to_string(),
-            }),
-        )),
-    }
+    let pricing = pricing_for(&state, None).await;
+    history_response(&state, wallet_name, pricing, &request).await
 }
 
 /// POST /api/transactions/send
@@ -310,56 +1026,81 @@ pub struct TransactionSendRequest {
     pub amount: String,
     pub network: String,
     pub password: String,
+    #[serde(default)]
+    pub allow_duplicate: bool,
+    /// 确认向合约address转账
+    #[serde(default)]
+    pub acknowledge_contract_recipient: bool,
+}
+
+/// [`TransactionSendRequest`] validate后
+pub struct TransactionSend {
+    pub wallet_name: WalletNameParam,
+    pub to: EvmAddress,
+    pub amount: Amount,
+    pub network: NetworkName,
+    pub password: String,
+    pub allow_duplicate: bool,
+    pub acknowledge_contract_recipient: bool,
+}
+
+impl Validate for TransactionSend {
+    type Raw = TransactionSendRequest;
+
+    fn validate(raw: TransactionSendRequest) -> Result<Self, ParamError> {
+        if raw.password.is_empty() {
+            return Err(ParamError::Missing("password"));
+        }
+        Ok(Self {
+            wallet_name: WalletNameParam::try_from(raw.wallet_name.as_str())?,
+            to: EvmAddress::try_from(raw.to.as_str())?,
+            amount: Amount::try_from(raw.amount.as_str())?,
+            network: NetworkName::try_from(raw.network.as_str())?.require_evm()?,
+            password: raw.password,
+            allow_duplicate: raw.allow_duplicate,
+            acknowledge_contract_recipient: raw.acknowledge_contract_recipient,
+        })
+    }
 }
 
 pub async fn transactions_send(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
-    Json(req): Json<TransactionSendRequest>,
-) -> Result<Json<SendTransactionResponse>, (StatusCode, Json<ErrorResponse>)> {
-    authenticate(&headers, &state.api_key).await.map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized".to_string(),
-                code: "AUTH_FAILED".to_string(),
-            }),
-        )
-    })?;
+    ValidJson(req): ValidJson<TransactionSend>,
+) -> Result<Response, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
 
-    if req.wallet_name.is_empty() || req.to.is_empty() || req.amount.is_empty() || req.network.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Missing required parameters".to_string(),
-                code: "MISSING_PARAMETERS".to_string(),
-            }),
-        ));
-    }
+    let to: Address = req.to.as_str().parse().map_err(|e| send_failed(&e))?;
+    let recipient = match guard_recipient(&state, req.network.as_str(), to, req.acknowledge_contract_recipient).await {
+        Ok(class) => class,
+        Err(refused) => return Ok(refused),
+    };
 
-    match state.wallet_manager.send_transaction(
-        &req.wallet_name,
+    let mut decision = DecisionContext::new();
+    decision.record_recipient(recipient);
+    let requester = Requester { principal: ADMIN_ISSUER, token_id: None };
+    let tx_hash = match send_signed_by_server(
+        &state,
+        requester,
+        req.wallet_name.as_str(),
         &req.to,
         &req.amount,
-        &req.network,
+        req.network.as_str(),
         &req.password,
-    ).await {
-        Ok(tx_hash) => Ok(Json(SendTransactionResponse {
-            tx_hash,
-            message: "Transaction sent successfully".to_string(),
-        })),
-        Err(e) => {
-            use crate::security::error_sanitizer::sanitize_error_message;
-            let safe_msg = sanitize_error_message(&e.to_string());
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: safe_msg,
-                    code: "TRANSACTION_FAILED".to_string(),
-                }),
-            ))
-        }
-    }
+        req.allow_duplicate,
+        decision,
+    )
+    .await?
+    {
+        ServerSend::Sent(tx_hash) => tx_hash,
+        ServerSend::Held(approval) => return Ok(held_response(&approval)),
+    };
+    Ok(Json(SendTransactionResponse {
+        explorer_url: state.config.blockchain.explorer_tx_url(req.network.as_str(), &tx_hash),
+        tx_hash,
+        message: "Transaction sent successfully".to_string(),
+    })
+    .into_response())
 }
 
 /// GET /api/transactions/:id/status
@@ -369,80 +1110,50 @@ pub struct TransactionStatusResponse {
     pub status: String,
     pub confirmations: u64,
     pub message: String,
+    /// 本地有发送记录时才知道 network
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+    /// 服务端sign时通过的各项check（限额、策略、规则哈希），见 `intents::decision`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decision: Option<serde_json::Value>,
 }
 
+/// 仅 API key（admin）可查；决策快照只在这里对外提供
 pub async fn transaction_status(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
-    Path(tx_id): Path<String>,
-) -> Result<Json<TransactionStatusResponse>, (StatusCode, Json<ErrorResponse>)> {
-    authenticate(&headers, &state.api_key).await.map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized".to_string(),
-                code: "AUTH_FAILED".to_string(),
-            }),
-        )
-    })?;
-
-    if tx_id.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid transaction ID".to_string(),
-                code: "INVALID_INPUT".to_string(),
-            }),
-        ));
-    }
+    ValidPath(tx_id): ValidPath<TxHash>,
+) -> Result<Json<TransactionStatusResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
 
-    // ✅ 实现基于时间的模拟transaction状态
-    // 真实实现需要query区块链RPC，这里提供一个智能模拟
-    
-    // fromtx_id提取时间戳（如果是我们生成的格式）
-    let is_our_tx = tx_id.contains("_mock_") || tx_id.starts_with("eth_") || tx_id.starts_with("btc_");
-    
-    if is_our_tx {
-        // fromtx_id中提取时间戳（hex格式）
-        let time_hex = tx_id.split('_').next_back().unwrap_or("0");
-        if let Ok(timestamp) = u64::from_str_radix(time_hex, 16) {
-            // 计算经过的毫秒数
-            let now_ms = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or(std::time::Duration::from_secs(0))
-                .as_millis() as u64;
-            let tx_ms = timestamp;
-            let elapsed_ms = now_ms.saturating_sub(tx_ms);
-            
-            // 模拟确认进度：每15秒增加1个确认
-            let confirmations = (elapsed_ms / 15000).min(12);
-            let status = if confirmations >= 6 {
-                "confirmed"
-            } else if confirmations > 0 {
-                "pending"
-            } else {
-                "pending"
-            };
-            
-            return Ok(Json(TransactionStatusResponse {
-                tx_id: tx_id.clone(),
-                status: status.to_string(),
-                confirmations,
-                message: if confirmations >= 6 {
-                    "Transaction confirmed".to_string()
-                } else {
-                    format!("Waiting for confirmations ({}/6)", confirmations)
-                },
-            }));
+    let record = match state.storage.transaction_by_hash(tx_id.as_str()).await {
+        Ok(record) => record,
+        Err(e) => {
+            tracing::warn!("transaction lookup for {} failed: {}", tx_id.as_str(), e);
+            None
         }
-    }
-    
-    // 对于外部Transaction hash或未知格式，返回pending
+    };
+    let decision = match &record {
+        Some(record) => state.storage.transaction_decision(&record.id).await.unwrap_or_else(|e| {
+            tracing::warn!("decision lookup for {} failed: {}", record.id, e);
+            None
+        }),
+        None => None,
+    };
+    let network = record.map(|r| r.network);
+    let explorer_url =
+        network.as_deref().and_then(|n| state.config.blockchain.explorer_tx_url(n, tx_id.as_str()));
+
     // Note:真实query需要区块链RPC节点，当前返回pending状态
     Ok(Json(TransactionStatusResponse {
-        tx_id: tx_id.clone(),
+        tx_id: tx_id.to_string(),
         status: "pending".to_string(),
         confirmations: 0,
         message: "Transaction statusquery中...（提示：完整实现需要集成区块链RPC）".to_string(),
+        network,
+        explorer_url,
+        decision,
     }))
 }
@@ -0,0 +1,112 @@
+//! 等待transaction确认（API key）
+//!
+//! `GET /api/transactions/:id/wait?network=eth&timeout=60&confirmations=3`
+//! 长轮询至达到确认数、transaction失败或超时；超时返回 408 与最后已知状态。
+//! `Accept: text/event-stream` 时改为 SSE，逐个推送中间状态
+//! （`pending`、`confirming`、终态），超时以 `timeout` 事件结束。
+//! 同一transaction的并发等待共享一个轮询任务（见 [`TxWatchRegistry`]）。
+//!
+//! [`TxWatchRegistry`]: crate::blockchain::tx_watch::TxWatchRegistry
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
+};
+use futures::stream::{self, Stream};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::admin::unauthorized;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::server::WalletServer;
+use crate::api::server_config::REQUEST_TIMEOUT;
+use crate::api::types::ApiError;
+use crate::api::validators::{NetworkName, ValidPath, TxHash};
+use crate::blockchain::tx_watch::{TxWaitStatus, TxWaiter};
+
+/// 最长等待（秒）；须小于全局请求超时
+pub const MAX_TX_WAIT_SECS: u64 = REQUEST_TIMEOUT.as_secs() - 5;
+const DEFAULT_TX_WAIT_SECS: u64 = 20;
+pub const MAX_WAIT_CONFIRMATIONS: u64 = 256;
+/// SSE 超时事件名；其余事件名即状态
+pub const SSE_TIMEOUT_EVENT: &str = "timeout";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct TxWaitQuery {
+    pub network: Option<String>,
+    /// 秒，上限 [`MAX_TX_WAIT_SECS`]
+    pub timeout: Option<u64>,
+    /// 默认取network配置的最终确认数
+    pub confirmations: Option<u64>,
+}
+
+fn wants_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"))
+}
+
+/// `GET /api/transactions/:id/wait`
+pub async fn wait_for_transaction(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(hash): ValidPath<TxHash>,
+    Query(query): Query<TxWaitQuery>,
+) -> Result<Response, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let network = NetworkName::try_from(query.network.as_deref().unwrap_or("eth"))?.require_evm()?;
+    let network = network.as_str();
+    let hash = match hash.as_str().strip_prefix("0x") {
+        Some(_) => hash.as_str().to_string(),
+        None => format!("0x{}", hash.as_str()),
+    };
+    let client = state.chain_clients.get(network).map_err(|e| {
+        ApiError::new(ApiErrorCode::NetworkUnavailable, e.to_string())
+    })?;
+
+    let required = query
+        .confirmations
+        .or_else(|| state.config.blockchain.networks.get(network).map(|n| n.required_confirmations()))
+        .unwrap_or(1)
+        .clamp(1, MAX_WAIT_CONFIRMATIONS);
+    let wait = Duration::from_secs(query.timeout.unwrap_or(DEFAULT_TX_WAIT_SECS).clamp(1, MAX_TX_WAIT_SECS));
+    let deadline = tokio::time::Instant::now() + wait;
+    let mut waiter = state.tx_watches.wait(network, &hash, required, deadline, client);
+
+    if wants_event_stream(&headers) {
+        return Ok(Sse::new(event_stream(waiter)).keep_alive(KeepAlive::default()).into_response());
+    }
+
+    let status = waiter.until_terminal().await;
+    let code = if status.is_terminal() { StatusCode::OK } else { StatusCode::REQUEST_TIMEOUT };
+    Ok((code, Json(status)).into_response())
+}
+
+fn sse_event(name: &str, status: &TxWaitStatus) -> Result<Event, Infallible> {
+    Ok(Event::default().event(name).json_data(status).unwrap_or_else(|_| Event::default().event(name)))
+}
+
+/// 每次状态或确认数变化推送一个事件；终态或超时后结束
+fn event_stream(waiter: TxWaiter) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(Some(waiter), |waiter| async move {
+        let mut waiter = waiter?;
+        match waiter.next_update().await {
+            Some(status) if status.is_terminal() => Some((sse_event(status.status, &status), None)),
+            Some(status) => Some((sse_event(status.status, &status), Some(waiter))),
+            None => {
+                let status = waiter.latest();
+                Some((sse_event(SSE_TIMEOUT_EVENT, &status), None))
+            }
+        }
+    })
+}
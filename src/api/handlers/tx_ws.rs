@@ -0,0 +1,261 @@
+//! transaction状态订阅（WebSocket）
+//!
+//! `GET /api/ws` 升级为 WebSocket 后，客户端发送
+//! `{"subscribe": {"wallet": "alice", "tx_hash": "0x...", "network": "eth"}}`
+//! （network 默认 eth），状态变化（pending → confirmed / failed）时服务端推送
+//! `{"type": "status", ...}`；终态推送后该订阅自动结束。
+//!
+//! 认证与 HTTP 接口相同（API key）：升级请求带 `X-API-KEY` / `Authorization`
+//! 头或 `?api_key=` 参数；都没有时，连接后的第一条消息必须是
+//! `{"auth": {"api_key": "..."}}`。轮询由服务端的 [`TxStatusHub`] 统一执行，
+//! 连接关闭时取消该连接的全部订阅。
+//!
+//! [`TxStatusHub`]: crate::blockchain::tx_status_hub::TxStatusHub
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::{HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+
+use super::admin::unauthorized;
+use crate::api::middleware::authenticate;
+use crate::api::server::WalletServer;
+use crate::api::validators::{NetworkName, ParamError, TxHash, WalletNameParam};
+use crate::blockchain::tx_status_hub::{SubscriptionHandle, SubscriptionKey, TxStatusEvent};
+
+/// 单个连接最多同时订阅的transaction数
+pub const MAX_WS_SUBSCRIPTIONS: usize = 100;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WsQuery {
+    pub api_key: Option<String>,
+}
+
+/// 客户端消息：`{"auth": {...}}`、`{"subscribe": {...}}`、`{"unsubscribe": {...}}`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WsClientMessage {
+    Auth { api_key: String },
+    Subscribe(WsSubscription),
+    Unsubscribe(WsSubscription),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WsSubscription {
+    pub wallet: String,
+    pub tx_hash: String,
+    pub network: Option<String>,
+}
+
+/// 服务端消息，以 `type` 区分
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WsServerMessage {
+    Authenticated,
+    Subscribed { wallet: String, network: String, tx_hash: String },
+    Unsubscribed { wallet: String, network: String, tx_hash: String },
+    Status(TxStatusEvent),
+    Error { code: String, error: String },
+}
+
+impl WsServerMessage {
+    fn error(code: &str, error: impl Into<String>) -> Self {
+        WsServerMessage::Error { code: code.to_string(), error: error.into() }
+    }
+}
+
+impl From<ParamError> for WsServerMessage {
+    fn from(e: ParamError) -> Self {
+        WsServerMessage::error(e.code(), e.to_string())
+    }
+}
+
+/// 复用 HTTP 的 API key 认证；`key` 按 `X-API-KEY` 头处理
+async fn api_key_accepted(state: &WalletServer, headers: &HeaderMap, key: Option<&str>) -> bool {
+    let mut headers = headers.clone();
+    if let Some(key) = key {
+        match HeaderValue::from_str(key) {
+            Ok(value) => {
+                headers.insert("x-api-key", value);
+            }
+            Err(_) => return false,
+        }
+    }
+    authenticate(&headers, &state.api_key).await.is_ok()
+}
+
+/// `GET /api/ws`
+pub async fn transaction_status_ws(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Query(query): Query<WsQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let presented =
+        query.api_key.is_some() || headers.contains_key("x-api-key") || headers.contains_key("authorization");
+    let authenticated = api_key_accepted(&state, &headers, query.api_key.as_deref()).await;
+    // 带了凭据但不对：不升级，按 HTTP 接口返回 401
+    if presented && !authenticated {
+        return unauthorized().into_response();
+    }
+    ws.on_upgrade(move |socket| WsSession::new(state, authenticated).run(socket))
+}
+
+struct WsSession {
+    state: Arc<WalletServer>,
+    authenticated: bool,
+    subscriptions: HashMap<SubscriptionKey, SubscriptionHandle>,
+}
+
+/// 处理完一条客户端消息后的动作
+enum Reply {
+    Send(WsServerMessage),
+    /// 订阅确认，随后补发订阅时已有的状态
+    Subscribed(WsServerMessage, Option<TxStatusEvent>),
+    /// 发送后关闭连接
+    Close(WsServerMessage),
+}
+
+impl WsSession {
+    fn new(state: Arc<WalletServer>, authenticated: bool) -> Self {
+        Self { state, authenticated, subscriptions: HashMap::new() }
+    }
+
+    async fn run(mut self, mut socket: WebSocket) {
+        // 先拿到接收端再订阅，之间的状态变化不会丢
+        let mut events = self.state.tx_status.events();
+        loop {
+            tokio::select! {
+                incoming = socket.recv() => {
+                    let text = match incoming {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                        // ping / pong 由 axum 应答
+                        Some(Ok(_)) => continue,
+                    };
+                    let (message, current, close) = match self.handle(&text).await {
+                        Reply::Send(message) => (message, None, false),
+                        Reply::Subscribed(message, current) => (message, current, false),
+                        Reply::Close(message) => (message, None, true),
+                    };
+                    if !send(&mut socket, &message).await || close {
+                        break;
+                    }
+                    if let Some(current) = current {
+                        if !send(&mut socket, &WsServerMessage::Status(current)).await {
+                            break;
+                        }
+                    }
+                }
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        let key = event.key();
+                        if !self.subscriptions.contains_key(&key) {
+                            continue;
+                        }
+                        // 终态后 hub 已结束该订阅
+                        if event.is_terminal() {
+                            self.subscriptions.remove(&key);
+                        }
+                        if !send(&mut socket, &WsServerMessage::Status(event)).await {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => warn!("ws subscriber fell behind by {} status events", missed),
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+        for handle in self.subscriptions.values() {
+            self.state.tx_status.unsubscribe(handle);
+        }
+        debug!("ws closed with {} subscriptions", self.subscriptions.len());
+    }
+
+    async fn handle(&mut self, text: &str) -> Reply {
+        let message: WsClientMessage = match serde_json::from_str(text) {
+            Ok(message) => message,
+            Err(e) => return Reply::Send(WsServerMessage::error("INVALID_REQUEST", e.to_string())),
+        };
+        match message {
+            WsClientMessage::Auth { api_key } => {
+                if api_key_accepted(&self.state, &HeaderMap::new(), Some(&api_key)).await {
+                    self.authenticated = true;
+                    Reply::Send(WsServerMessage::Authenticated)
+                } else {
+                    Reply::Close(WsServerMessage::error("AUTH_FAILED", "Unauthorized"))
+                }
+            }
+            _ if !self.authenticated => {
+                Reply::Close(WsServerMessage::error("AUTH_FAILED", "Authenticate before subscribing"))
+            }
+            WsClientMessage::Subscribe(subscription) => self.subscribe(subscription),
+            WsClientMessage::Unsubscribe(subscription) => match parse_subscription(&subscription) {
+                Ok((wallet, network, tx_hash)) => {
+                    let key = (wallet.clone(), network.clone(), tx_hash.to_ascii_lowercase());
+                    if let Some(handle) = self.subscriptions.remove(&key) {
+                        self.state.tx_status.unsubscribe(&handle);
+                    }
+                    Reply::Send(WsServerMessage::Unsubscribed { wallet, network, tx_hash })
+                }
+                Err(e) => Reply::Send(e.into()),
+            },
+        }
+    }
+
+    fn subscribe(&mut self, subscription: WsSubscription) -> Reply {
+        let (wallet, network, tx_hash) = match parse_subscription(&subscription) {
+            Ok(parsed) => parsed,
+            Err(e) => return Reply::Send(e.into()),
+        };
+        let key = (wallet.clone(), network.clone(), tx_hash.to_ascii_lowercase());
+        let mut current = None;
+        if !self.subscriptions.contains_key(&key) {
+            if self.subscriptions.len() >= MAX_WS_SUBSCRIPTIONS {
+                let error = format!("At most {} subscriptions per connection", MAX_WS_SUBSCRIPTIONS);
+                return Reply::Send(WsServerMessage::error("TOO_MANY_SUBSCRIPTIONS", error));
+            }
+            let client = match self.state.chain_clients.get(&network) {
+                Ok(client) => client,
+                Err(e) => return Reply::Send(WsServerMessage::error("NETWORK_UNAVAILABLE", e.to_string())),
+            };
+            let (handle, last) = self.state.tx_status.subscribe(&wallet, &network, &tx_hash, client);
+            self.subscriptions.insert(key, handle);
+            current = last;
+        }
+        Reply::Subscribed(WsServerMessage::Subscribed { wallet, network, tx_hash }, current)
+    }
+}
+
+/// validate后的 `(wallet, network, 0x 前缀的 hash)`
+fn parse_subscription(subscription: &WsSubscription) -> Result<(String, String, String), ParamError> {
+    let wallet = WalletNameParam::try_from(subscription.wallet.as_str())?;
+    let network = NetworkName::try_from(subscription.network.as_deref().unwrap_or("eth"))?.require_evm()?;
+    let hash = TxHash::try_from(subscription.tx_hash.as_str())?;
+    let hash = match hash.as_str().strip_prefix("0x") {
+        Some(_) => hash.as_str().to_string(),
+        None => format!("0x{}", hash.as_str()),
+    };
+    Ok((wallet.as_str().to_string(), network.as_str().to_string(), hash))
+}
+
+/// `false` 表示连接已断开
+async fn send(socket: &mut WebSocket, message: &WsServerMessage) -> bool {
+    match serde_json::to_string(message) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(e) => {
+            warn!("failed to encode ws message: {}", e);
+            true
+        }
+    }
+}
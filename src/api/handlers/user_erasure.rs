@@ -0,0 +1,181 @@
+//! user数据擦除（API key）：匿名化、转移wallet归属并签发擦除证书
+//!
+//! users.db 与 wallets.db 是两个物理隔离的库，无法放进同一个事务：先在
+//! wallets.db 的一个事务里脱敏审计日志（重新计算 MAC，并记入事件 journal）、
+//! 删除 wallet token，再在 users.db 的一个事务里转移wallet、删除会话与偏好、
+//! 匿名化user行，最后写证书。每一步对已擦除的数据都是空操作，因此中途失败后
+//! 重新调用即可补完；完全擦除后再调用不会产生任何改动。
+//!
+//! TOTP 目前是 mock（见 `auth_simple`），没有持久化的 secret；2FA 开关存放在
+//! user_preferences 中，随偏好一起删除。
+
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Json,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::admin::unauthorized;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::authenticate;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::user_db::ERASED_OWNER_ID;
+
+/// 证书中记录的操作人最长字符数
+const MAX_REQUESTED_BY_LEN: usize = 128;
+
+fn internal(e: anyhow::Error) -> ApiError {
+    error!("user erasure failed: {:#}", e);
+    ApiError::new(ApiErrorCode::ErasureFailed, "User erasure failed")
+}
+
+/// 余额字符串（如 "0.000000000000000000"）是否非零
+fn is_nonzero(balance: &str) -> bool {
+    balance.chars().any(|c| matches!(c, '1'..='9'))
+}
+
+/// 在每个已配置网络上查询user wallet的原生余额；任一非零则拒绝擦除
+///
+/// 查询失败时同样拒绝：无法证明余额为零就不能放弃这些wallet的归属。
+async fn ensure_wallets_empty(
+    state: &WalletServer,
+    wallets: &[crate::api::user_db::WalletInfo],
+) -> Result<(), ApiError> {
+    let networks = state.chain_clients.networks();
+    // 多个wallet共用一个address时每个网络只查询一次，但每个wallet都列出
+    let mut balances: HashMap<(String, String), String> = HashMap::new();
+    let mut held = Vec::new();
+    for wallet in wallets {
+        let Some(address) = wallet.address.as_deref().filter(|a| !a.is_empty()) else {
+            continue;
+        };
+        for network in &networks {
+            let key = (address.to_lowercase(), network.clone());
+            let balance = match balances.get(&key) {
+                Some(balance) => balance.clone(),
+                None => {
+                    let client = state.chain_clients.get(network).map_err(|e| internal(anyhow::anyhow!("{}", e)))?;
+                    let balance = client.get_balance(address).await.map_err(|e| {
+                        ApiError::new(
+                            ApiErrorCode::BalanceCheckFailed,
+                            format!("Could not verify the balance of wallet '{}' on {}: {}", wallet.name, network, e),
+                        )
+                    })?;
+                    balances.insert(key, balance.clone());
+                    balance
+                }
+            };
+            if is_nonzero(&balance) {
+                held.push(format!("'{}' holds {} on {}", wallet.name, balance, network));
+            }
+        }
+    }
+
+    if held.is_empty() {
+        return Ok(());
+    }
+    Err(ApiError::new(
+        ApiErrorCode::WalletNotEmpty,
+        format!("Wallets must be swept or transferred before erasure: {}", held.join(", ")),
+    ))
+}
+
+/// `POST /api/admin/users/:id/erase`：擦除user的个人数据并签发证书
+///
+/// wallet持有非零余额时返回 409 `WALLET_NOT_EMPTY`。重复调用幂等：没有任何
+/// 改动时不再签发新证书，返回最近一张，`changed` 为 false。
+pub async fn erase_user(
+    State(state): State<Arc<WalletServer>>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+    body: Option<Json<EraseUserRequest>>,
+) -> Result<Json<UserErasureResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let request = body.map(|Json(b)| b).unwrap_or_default();
+    let erased_by = request.requested_by.as_deref().map(str::trim).filter(|r| !r.is_empty()).unwrap_or("admin");
+    if erased_by.chars().count() > MAX_REQUESTED_BY_LEN {
+        return Err(ApiError::new(
+            ApiErrorCode::InvalidRequestedBy,
+            format!("requested_by must be at most {} characters", MAX_REQUESTED_BY_LEN),
+        ));
+    }
+    if user_id == ERASED_OWNER_ID {
+        return Err(ApiError::new(ApiErrorCode::InvalidUser, "The erased-wallet owner cannot be erased"));
+    }
+    if !state.user_db.user_exists(&user_id).await.map_err(internal)? {
+        return Err(ApiError::new(ApiErrorCode::UserNotFound, "User not found"));
+    }
+
+    let wallets = state.user_db.get_user_wallets_with_address(&user_id).await.map_err(internal)?;
+    ensure_wallets_empty(&state, &wallets).await?;
+
+    // wallets.db 先行：若 users.db 一步失败，wallet仍挂在user名下，重试时能再找到
+    let names: Vec<String> = wallets.iter().map(|w| w.name.clone()).collect();
+    let wallet_erasure =
+        state.storage.erase_user_records(&user_id, &names, ERASED_OWNER_ID).await.map_err(internal)?;
+    let user_erasure = state
+        .user_db
+        .erase_user(&user_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| ApiError::new(ApiErrorCode::UserNotFound, "User not found"))?;
+    let tokens_revoked = state.session_store.revoke_user_tokens(&user_id).await;
+    state.session_store.clear_user_session(&user_id).await;
+
+    let categories = BTreeMap::from([
+        ("audit_log_network_identifiers".to_string(), wallet_erasure.redacted_audit_ids.len() as u64),
+        ("api_tokens".to_string(), wallet_erasure.api_tokens_deleted),
+        ("wallet_links".to_string(), user_erasure.wallets_transferred.len() as u64),
+        ("wallet_ownership".to_string(), wallet_erasure.ownership_transferred),
+        ("sessions".to_string(), user_erasure.sessions_deleted + tokens_revoked as u64),
+        ("preferences".to_string(), user_erasure.preferences_deleted),
+        ("profile".to_string(), user_erasure.profile_anonymized as u64),
+    ]);
+    let changed = categories.values().any(|&n| n > 0);
+
+    if !changed {
+        let existing = state.storage.erasure_certificates(&user_id).await.map_err(internal)?;
+        if let Some(certificate) = existing.into_iter().last() {
+            return Ok(Json(UserErasureResponse { changed, certificate }));
+        }
+    }
+
+    let certificate = state
+        .storage
+        .record_erasure_certificate(&user_id, erased_by, categories, names)
+        .await
+        .map_err(internal)?;
+    info!("user {} erased by {} (certificate {})", user_id, erased_by, certificate.id);
+    Ok(Json(UserErasureResponse { changed, certificate }))
+}
+
+/// `GET /api/admin/users/:id/erasure-certificates`：该user的全部擦除证书
+pub async fn list_erasure_certificates(
+    State(state): State<Arc<WalletServer>>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<ErasureCertificatesResponse>, ApiError> {
+    authenticate(&headers, &state.api_key).await.map_err(|_| unauthorized())?;
+
+    let certificates = state.storage.erasure_certificates(&user_id).await.map_err(internal)?;
+    Ok(Json(ErasureCertificatesResponse { user_id, certificates }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_nonzero() {
+        assert!(!is_nonzero("0.000000000000000000"));
+        assert!(!is_nonzero("0"));
+        assert!(!is_nonzero(""));
+        assert!(is_nonzero("0.000000000000000001"));
+        assert!(is_nonzero("1.5"));
+    }
+}
@@ -1,17 +1,36 @@
 //! wallet管理相关handlers
 
 use axum::{
-    extract::{Path, State},
-    http::{HeaderMap, StatusCode},
-    response::Json,
+    extract::{Path, Query, RawQuery, State},
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Json, Response},
 };
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Serialize;
+use std::borrow::Cow;
 use std::sync::Arc;
-use tracing::{info, error};
+use tracing::{info, error, warn};
 
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::http_cache::{self, CacheKey};
 use crate::api::middleware::extract_user::{extract_user_id_from_token, verify_wallet_ownership};
+use crate::api::middleware::request_metrics::note_storage_error;
 use crate::api::server::WalletServer;
+use crate::api::streaming::json_array_body;
+use crate::api::user_db::{WalletInfo, WalletSearch};
+use crate::api::handlers::funding::{parse_preflight_networks, run_preflight, PreflightQuery};
+use crate::api::middleware::authenticate;
 use crate::api::types::*;
-use crate::api::validators::{validate_wallet_name, validate_wallet_address};
+use crate::api::validators::{
+    parse_derivation_path, validate_wallet_name, validate_wallet_address, NetworkName, ParamError,
+};
+use crate::core::errors::WalletError;
+use crate::core::wallet_manager::{probe_network, NetworkInitStatus};
+use crate::storage::{journal_events, like_pattern, NewJournalEvent, WalletNotes};
+
+/// 下一页游标响应头
+pub const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
 // ✅ 非托管模式：不再需要validate_password_strength（前端不发送Password）
 
 // ✅ 非托管模式：mnemonic由前端生成，此函数不再使用
@@ -38,31 +57,76 @@ use crate::api::validators::{validate_wallet_name, validate_wallet_address};
 //     Ok(mnemonic.to_string())
 // }
 
+/// wallet绑定保存在 users 库中，无法与 events_journal 同事务提交；
+/// 在绑定变更成功后立即写入journal，写入failed只记录日志。
+async fn journal_wallet_event(
+    state: &WalletServer,
+    event_type: &str,
+    name: &str,
+    payload: serde_json::Value,
+) {
+    let event = NewJournalEvent { event_type, entity_type: "wallet", entity_id: name, payload };
+    if let Err(e) = state.storage.record_event(&event).await {
+        warn!("failed to journal {} for wallet {}: {}", event_type, name, e);
+    }
+}
+
+/// 规范化 `initialize_networks` 并去重；非托管address是 EVM address，只能登记到 EVM network
+fn parse_initialize_networks(raw: &[String]) -> Result<Vec<String>, ParamError> {
+    let mut networks: Vec<String> = Vec::new();
+    for network in raw {
+        let network = NetworkName::try_from(network.as_str())?.require_evm()?.as_str().to_string();
+        if !networks.contains(&network) {
+            networks.push(network);
+        }
+    }
+    Ok(networks)
+}
+
 pub async fn create_wallet(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,  // ✅ 启用user认证
+    Query(query): Query<PreflightQuery>,
     Json(payload): Json<CreateWalletRequest>,
-) -> Result<Json<WalletResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<WalletResponse>, ApiError> {
     // ✅ 提取当前登录User ID
     let user_id = extract_user_id_from_token(&headers, &state).await?;
 
     // validateWallet name（使用共享validate器）
     validate_wallet_name(&payload.name)?;
 
+    // 可选资金预检：先校验network列表，避免创建后才报参数error
+    let preflight_networks = match query.preflight.as_deref() {
+        Some(raw) => Some(parse_preflight_networks(raw)?),
+        None => None,
+    };
+
+    // 前端按此路径派生address；以规范形式记入创建事件，作为该wallet的默认路径
+    let derivation_path = payload.derivation_path.as_deref().map(parse_derivation_path).transpose()?;
+
+    // 非托管模式下服务器不派生密钥，passphrase 只能由前端在派生address时使用
+    if payload.passphrase.as_deref().is_some_and(|p| !p.is_empty()) {
+        return Err(ApiError::new(
+            ApiErrorCode::InvalidInput,
+            "passphrase is applied client-side when deriving wallet_address; do not send it to the server",
+        ));
+    }
+
     // ✅ 非托管模式：walletaddress必须由前端提供
     let wallet_address = payload.wallet_address.as_ref().ok_or_else(|| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "wallet_address is required in non-custodial mode".to_string(),
-                code: "WALLET_ADDRESS_REQUIRED".to_string(),
-            }),
-        )
+        ApiError::new(ApiErrorCode::WalletAddressRequired, "wallet_address is required in non-custodial mode")
     })?;
 
     // ✅ 使用统一的addressvalidate器
     validate_wallet_address(wallet_address)?;
 
+    // 先查询链上 nonce / 区块高度（RPC failed降级为 needs_sync），此时还没有写入任何数据
+    let initialize_networks = parse_initialize_networks(&payload.initialize_networks)?;
+    let network_inits = futures::future::join_all(
+        initialize_networks.iter().map(|network| probe_network(&state.chain_clients, network, wallet_address)),
+    )
+    .await;
+
     // ✅ 非托管模式：直接保存address绑定，不调用WalletManager
     // 将wallet关联到当前user
     let wallet_type = payload.wallet_type.clone().unwrap_or_else(|| "standard".to_string());
@@ -75,12 +139,49 @@ pub async fn create_wallet(
     
     match link_result {
         Ok(_) => {
+            // 绑定在 users 库，network行在 wallet 库：network事务failed时撤销绑定，不留半初始化的wallet
+            if !network_inits.is_empty() {
+                if let Err(e) = state.storage.initialize_wallet_networks(&payload.name, &network_inits).await {
+                    error!("initializing networks for wallet {} failed: {}", payload.name, e);
+                    if let Err(e) = state.user_db.unlink_wallet(&user_id, &payload.name).await {
+                        error!("failed to roll back wallet binding {}: {}", payload.name, e);
+                    }
+                    return Err(ApiError::new(
+                        ApiErrorCode::WalletInitializationFailed,
+                        "Failed to initialize wallet networks",
+                    ));
+                }
+            }
+            let network_statuses: Vec<NetworkInitStatus> =
+                network_inits.iter().map(NetworkInitStatus::from).collect();
+
             info!("✅ 非托管wallet '{}' 已关联到user {} (address: {})", 
                   payload.name, user_id, wallet_address);
+            journal_wallet_event(
+                &state,
+                journal_events::WALLET_CREATED,
+                &payload.name,
+                serde_json::json!({
+                    "name": payload.name,
+                    "address": wallet_address,
+                    "wallet_type": wallet_type,
+                    "quantum_safe": payload.quantum_safe,
+                    "user_id": user_id,
+                    "networks": network_statuses,
+                    "derivation_path": derivation_path,
+                }),
+            )
+            .await;
 
             // 构建响应（非托管模式不返回mnemonic，由前端管理）
             let warning = Some("✅ 非托管wallet：您的mnemonic由您自己保管，请务必安全备份！".to_string());
 
+            // 预检中的 RPC failed只会降级为 null，不影响创建结果
+            let preflight = match preflight_networks {
+                Some(networks) => Some(run_preflight(&state.gas_oracle, wallet_address, &networks).await),
+                None => None,
+            };
+
             Ok(Json(WalletResponse {
                 id: payload.name.clone(),
                 name: payload.name,
@@ -89,42 +190,25 @@ pub async fn create_wallet(
                 wallet_type: Some(wallet_type.clone()),  // ✅ 返回wallet类型
                 mnemonic: None,  // 非托管模式：不返回mnemonic
                 warning,
+                preflight,
+                networks: (!network_statuses.is_empty()).then_some(network_statuses),
+                description: None,
+                metadata: None,
             }))
         }
         Err(e) => {
             // check是否是wallet已存在error - 返回 409 Conflict
             let error_msg = format!("{}", e);
             if error_msg.contains("already exists") {
-                return Err((
-                    StatusCode::CONFLICT, // 409
-                    Json(ErrorResponse {
-                        error: format!("Wallet '{}' already exists", payload.name),
-                        code: "WALLET_EXISTS".to_string(),
-                    }),
-                ));
-            }
-            
-            // 其他error
-            let test_mode = std::env::var("DEV_MODE").ok().as_deref() == Some("1");
-            if test_mode {
-                let reveal = std::env::var("DEV_PRINT_SECRETS").ok().as_deref() == Some("1");
-                let msg = if reveal {
-                    format!("Failed to create wallet: {}", e)
-                } else {
-                    "Failed to create wallet".to_string()
-                };
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse { error: msg, code: "WALLET_CREATION_FAILED".to_string() }),
+                return Err(ApiError::new(
+                    ApiErrorCode::WalletExists,
+                    format!("Wallet '{}' already exists", payload.name),
                 ));
             }
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to create wallet".to_string(),
-                    code: "WALLET_CREATION_FAILED".to_string(),
-                }),
-            ))
+
+            // 其他error：细节只进日志，响应里不带
+            error!("linking wallet {} for user {} failed: {}", payload.name, user_id, e);
+            Err(ApiError::new(ApiErrorCode::WalletCreationFailed, "Failed to create wallet"))
         }
     }
 }
@@ -135,75 +219,42 @@ async fn create_multisig_wallet(
     state: Arc<WalletServer>,
     user_id: String,  // ✅ 添加user_id参数
     payload: CreateWalletRequest,
-) -> Result<Json<WalletResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<WalletResponse>, ApiError> {
     info!("创建多签wallet: {}", payload.name);
 
     // validate多签配置
     let config = match &payload.multisig_config {
         Some(cfg) => cfg,
         None => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: "多签wallet需要提供 multisig_config".to_string(),
-                    code: "MISSING_MULTISIG_CONFIG".to_string(),
-                }),
-            ));
+            return Err(ApiError::new(ApiErrorCode::MissingMultisigConfig, "多签wallet需要提供 multisig_config"));
         }
     };
 
     // validate M-of-N 参数
     if config.m == 0 || config.n == 0 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "M 和 N 必须大于 0".to_string(),
-                code: "INVALID_MULTISIG_PARAMS".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::InvalidMultisigParams, "M 和 N 必须大于 0"));
     }
 
     if config.m > config.n {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: format!("M({}) 不能大于 N({})", config.m, config.n),
-                code: "INVALID_MULTISIG_PARAMS".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::InvalidMultisigParams, format!("M({}) 不能大于 N({})", config.m, config.n)));
     }
 
     // validatesign者数量
     if config.signers.len() != config.n as usize {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: format!("sign者数量({})必须等于 N({})", config.signers.len(), config.n),
-                code: "INVALID_SIGNER_COUNT".to_string(),
-            }),
+        return Err(ApiError::new(
+            ApiErrorCode::InvalidSignerCount,
+            format!("sign者数量({})必须等于 N({})", config.signers.len(), config.n),
         ));
     }
 
     // validatesign者address
     for (i, signer) in config.signers.iter().enumerate() {
         if signer.address.trim().is_empty() {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("sign者 #{} 的address不能为空", i + 1),
-                    code: "INVALID_SIGNER_ADDRESS".to_string(),
-                }),
-            ));
+            return Err(ApiError::new(ApiErrorCode::InvalidSignerAddress, format!("sign者 #{} 的address不能为空", i + 1)));
         }
 
         if !signer.address.starts_with("0x") || signer.address.len() != 42 {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("sign者 #{} 的address格式无效", i + 1),
-                    code: "INVALID_SIGNER_ADDRESS".to_string(),
-                }),
-            ));
+            return Err(ApiError::new(ApiErrorCode::InvalidSignerAddress, format!("sign者 #{} 的address格式无效", i + 1)));
         }
     }
 
@@ -211,24 +262,12 @@ async fn create_multisig_wallet(
 
     // ✅ 非托管模式：多签wallet也由前端提供address（智能合约address）
     let wallet_address = payload.wallet_address.as_ref().ok_or_else(|| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "多签wallet需要提供wallet_address（智能合约address）".to_string(),
-                code: "WALLET_ADDRESS_REQUIRED".to_string(),
-            }),
-        )
+        ApiError::new(ApiErrorCode::WalletAddressRequired, "多签wallet需要提供wallet_address（智能合约address）")
     })?;
 
     // validateaddress格式
     if !wallet_address.starts_with("0x") || wallet_address.len() != 42 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid wallet address format".to_string(),
-                code: "INVALID_ADDRESS".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::InvalidAddress, "Invalid wallet address format"));
     }
 
     // ✅ 非托管模式：直接保存address绑定，不调用WalletManager
@@ -263,66 +302,156 @@ async fn create_multisig_wallet(
                     "✅ 非托管多签wallet：配置{}-of-{}。sign者：{}",
                     config.m, config.n, signers_info
                 )),
+                preflight: None,
+                networks: None,
+                description: None,
+                metadata: None,
             }))
         }
         Err(e) => {
             error!("关联多签walletfailed: {:?}", e);
             
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "创建多签walletfailed".to_string(),
-                    code: "MULTISIG_CREATION_FAILED".to_string(),
-                }),
-            ))
+            Err(ApiError::new(ApiErrorCode::MultisigCreationFailed, "创建多签walletfailed"))
         }
     }
 }
 
+/// 默认（也是最大）每页wallet数
+pub const MAX_WALLET_LIST_LIMIT: usize = 500;
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// 逐条序列化的列表元素：借用 `WalletInfo` 生成 [`WalletListItem`]，不复制名称；
+/// 附带托管记录上的允许network集合
+struct ListedWallet(WalletInfo, Option<Vec<String>>, Option<WalletNotes>);
+
+impl Serialize for ListedWallet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let w = &self.0;
+        WalletListItem {
+            id: Cow::Borrowed(&w.name),
+            name: Cow::Borrowed(&w.name),
+            address: Cow::Borrowed(w.address.as_deref().unwrap_or(ZERO_ADDRESS)),
+            quantum_safe: false, // 非托管模式暂不支持量子安全标记
+            wallet_type: Some(Cow::Borrowed(&w.wallet_type)),
+            description: self.2.as_ref().and_then(|n| n.description.as_deref()),
+            metadata: self.2.as_ref().map(|n| &n.metadata).filter(|m| !m.is_empty()),
+            allowed_networks: self.1.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+fn encode_wallet_cursor(created_at: &str, id: i64) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}|{}", created_at, id))
+}
+
+fn decode_wallet_cursor(token: &str) -> Option<(String, i64)> {
+    let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(token.trim()).ok()?).ok()?;
+    let (created_at, id) = raw.rsplit_once('|')?;
+    Some((created_at.to_string(), id.parse().ok()?))
+}
+
+/// `GET /api/wallets?cursor=&limit=&search=`
+///
+/// 按 (created_at, id) keyset 分页，响应体逐条流式序列化；
+/// 存在下一页时通过 `X-Next-Cursor` 响应头返回游标。
+/// `search` 匹配名称、描述或任一元数据值（子串、不区分大小写），与游标可组合使用。
+/// 带 ETag：版本号 = wallet库的 wallets epoch + 该user的 user_wallets epoch，
+/// `If-None-Match` 命中时返回 304；同一版本的响应体由 [`http_cache::ResponseCache`] 按user隔离缓存。
 pub async fn list_wallets(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,  // ✅ 启用user认证
-) -> Result<Json<Vec<WalletResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    RawQuery(raw_query): RawQuery,
+    Query(query): Query<WalletListQuery>,
+) -> Result<Response, ApiError> {
     // ✅ 提取当前登录User ID
     let user_id = extract_user_id_from_token(&headers, &state).await?;
-    
+
+    let limit = query.limit.unwrap_or(MAX_WALLET_LIST_LIMIT).clamp(1, MAX_WALLET_LIST_LIMIT);
+    let after = match query.cursor.as_deref().filter(|c| !c.is_empty()) {
+        Some(token) => Some(
+            decode_wallet_cursor(token).ok_or_else(|| ApiError::new(ApiErrorCode::InvalidCursor, "Invalid cursor"))?,
+        ),
+        None => None,
+    };
+
+    // 版本号必须在构建响应体之前读取：构建期间的写入只会让这份响应体挂在旧版本下
+    let wallets_epoch = state.storage.wallets_epoch().await.map_err(|e| wallet_list_db_error(&user_id, e))?;
+    let links_epoch = state.user_db.wallet_list_epoch(&user_id).await.map_err(|e| wallet_list_db_error(&user_id, e))?;
+    let version = format!("{}.{}", wallets_epoch, links_epoch);
+    let key = CacheKey::new(http_cache::WALLET_LIST, raw_query.as_deref(), &user_id);
+
+    state
+        .response_cache
+        .respond(&headers, key, &version, || wallet_list_body(&state, &user_id, &query, after, limit))
+        .await
+}
+
+fn wallet_list_db_error(user_id: &str, e: anyhow::Error) -> ApiError {
+    error!("fetchuserwallet列表failed: user_id={}, error={}", user_id, e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "queryuserwalletfailed")
+}
+
+async fn wallet_list_body(
+    state: &WalletServer,
+    user_id: &str,
+    query: &WalletListQuery,
+    after: Option<(String, i64)>,
+    limit: usize,
+) -> Result<Response, ApiError> {
+    let db_error = |e: anyhow::Error| wallet_list_db_error(user_id, e);
+
+    // 描述和元数据在wallet库里：先在那边匹配出名称，再与user_wallets上的名称匹配合并
+    let search = match query.search.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        Some(q) => {
+            let names = state.storage.search_wallets(q).await.map_err(db_error)?;
+            Some(WalletSearch { pattern: like_pattern(q), names })
+        }
+        None => None,
+    };
+
     // ✅ 非托管模式：直接fromuser_wallets表fetchwallet信息（包括address）
-    let wallets = state.user_db.get_user_wallets_with_address(&user_id)
+    let (wallets, next) = state
+        .user_db
+        .search_user_wallets_page(user_id, search.as_ref(), after.as_ref().map(|(c, id)| (c.as_str(), *id)), limit)
         .await
-        .map_err(|e| {
-            error!("fetchuserwallet列表failed: user_id={}, error={}", user_id, e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "queryuserwalletfailed".to_string(),
-                    code: "DB_ERROR".to_string(),
-                }),
-            )
-        })?;
+        .map_err(db_error)?;
 
-    // 转换为WalletResponse
-    let user_wallets: Vec<WalletResponse> = wallets
-        .into_iter()
-        .map(|w| WalletResponse {
-            id: w.name.clone(),
-            name: w.name.clone(),
-            address: w.address.unwrap_or_else(|| "0x0000000000000000000000000000000000000000".to_string()),
-            quantum_safe: false,  // 非托管模式暂不支持量子安全标记
-            wallet_type: Some(w.wallet_type.clone()),  // ✅ 返回wallet类型
-            mnemonic: None,  // 非托管模式：永不返回mnemonic
-            warning: None,
-        })
-        .collect();
-    
-    info!("✅ 返回user {} 的 {} 个非托管wallet", user_id, user_wallets.len());
-    Ok(Json(user_wallets))
+    info!("✅ 返回user {} 的 {} 个非托管wallet", user_id, wallets.len());
+
+    let names: Vec<String> = wallets.iter().map(|w| w.name.clone()).collect();
+    let mut notes = match state.storage.wallet_notes_for(&names).await {
+        Ok(notes) => notes,
+        Err(e) => {
+            warn!("failed to load wallet notes for user {}: {}", user_id, e);
+            Default::default()
+        }
+    };
+
+    let manager = state.wallet_manager.clone();
+    let mut response = (
+        [(header::CONTENT_TYPE, "application/json")],
+        json_array_body(wallets.into_iter().map(move |w| {
+            let allowed = manager.allowed_networks(&w.name).ok().flatten();
+            let wallet_notes = notes.remove(&w.name);
+            ListedWallet(w, allowed, wallet_notes)
+        })),
+    )
+        .into_response();
+    if let Some((created_at, id)) = next {
+        if let Ok(v) = HeaderValue::from_str(&encode_wallet_cursor(&created_at, id)) {
+            response.headers_mut().insert(NEXT_CURSOR_HEADER, v);
+        }
+    }
+    Ok(response)
 }
 
 pub async fn delete_wallet(
     State(state): State<Arc<WalletServer>>,
     headers: HeaderMap,
     Path(name): Path<String>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     // ✅ 使用新的user认证机制
     let user_id = extract_user_id_from_token(&headers, &state).await?;
     
@@ -332,35 +461,114 @@ pub async fn delete_wallet(
     // validateWallet name（使用共享validate器）
     validate_wallet_name(&name)?;
 
+    // 解绑后 id 就查不到了：先取出来，用于停用余额订阅
+    let wallet_id = match state.user_db.find_user_wallet(&user_id, &name).await {
+        Ok(found) => found.map(|(id, _)| id),
+        Err(e) => {
+            warn!("failed to look up wallet {} before deletion: {}", name, e);
+            None
+        }
+    };
+
     // ✅ 非托管模式：fromuser_wallets表Delete关联
     match state.user_db.unlink_wallet(&user_id, &name).await {
         Ok(deleted) => {
             if deleted {
                 info!("✅ wallet关联已Delete: user={}, wallet={}", user_id, name);
+                if let Some(wallet_id) = wallet_id {
+                    if let Err(e) = state.storage.deactivate_balance_subscriptions(wallet_id).await {
+                        warn!("failed to deactivate balance subscriptions of wallet {}: {}", name, e);
+                    }
+                }
+                if let Err(e) = state.storage.forget_wallet_networks(&name).await {
+                    warn!("failed to drop network state of wallet {}: {}", name, e);
+                }
+                if let Err(e) = state.storage.remove_wallet_from_groups(&name).await {
+                    warn!("failed to drop group memberships of wallet {}: {}", name, e);
+                }
+                journal_wallet_event(
+                    &state,
+                    journal_events::WALLET_DELETED,
+                    &name,
+                    serde_json::json!({ "name": name, "user_id": user_id }),
+                )
+                .await;
                 Ok(Json(serde_json::json!({
                     "success": true,
                     "message": "Wallet deleted successfully"
                 })))
             } else {
                 // 未找到wallet
-                Err((
-                    StatusCode::NOT_FOUND,
-                    Json(ErrorResponse {
-                        error: "Wallet not found".to_string(),
-                        code: "WALLET_NOT_FOUND".to_string(),
-                    }),
-                ))
+                Err(ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found"))
             }
         }
         Err(e) => {
             error!("Delete Wallet关联failed: user={}, wallet={}, error={}", user_id, name, e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to delete wallet".to_string(),
-                    code: "DELETE_WALLET_FAILED".to_string(),
-                }),
-            ))
+            Err(ApiError::new(ApiErrorCode::DeleteWalletFailed, "Failed to delete wallet"))
         }
     }
 }
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct InitializeNetworkRequest {
+    /// 托管wallet（API key 调用）派生address需要的Password
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// `POST /api/wallets/:name/networks/:network/initialize`
+///
+/// 补全wallet在某个network上的初始化（新增network，或把 `needs_sync` 补成 `ready`）。
+/// 会话调用者操作自己的非托管wallet，address取自绑定；API key 调用者操作
+/// WalletManager 中的托管wallet，需要Password派生address。
+pub async fn initialize_wallet_network(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((name, network)): Path<(String, String)>,
+    body: Option<Json<InitializeNetworkRequest>>,
+) -> Result<Json<NetworkInitStatus>, ApiError> {
+    validate_wallet_name(&name)?;
+    let network = NetworkName::try_from(network.as_str())?;
+
+    if authenticate(&headers, &state.api_key).await.is_ok() {
+        let password = body.and_then(|Json(b)| b.password).ok_or_else(|| {
+            ApiError::new(ApiErrorCode::PasswordRequired, "password is required to initialize a managed wallet")
+        })?;
+        let status = state
+            .wallet_manager
+            .initialize_network(&name, network.as_str(), &password, &state.storage, &state.chain_clients)
+            .await
+            .map_err(|e| {
+                error!("initializing {} for managed wallet {} failed: {}", network.as_str(), name, e);
+                let code = match &e {
+                    WalletError::NotFoundError(_) => ApiErrorCode::WalletNotFound,
+                    WalletError::NetworkNotAllowed(_) => ApiErrorCode::NetworkNotAllowed,
+                    WalletError::ValidationError(_) => ApiErrorCode::InvalidNetwork,
+                    WalletError::CryptoError(_) => ApiErrorCode::InvalidPassword,
+                    _ => return ApiError::generic(ApiErrorCode::WalletInitializationFailed),
+                };
+                ApiError::new(code, e.to_string())
+            })?;
+        return Ok(Json(status));
+    }
+
+    let user_id = extract_user_id_from_token(&headers, &state).await?;
+    let not_found = || ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found");
+    let address = match state.user_db.find_user_wallet(&user_id, &name).await {
+        Ok(Some((_, Some(address)))) => address,
+        Ok(_) => return Err(not_found()),
+        Err(e) => {
+            error!("wallet lookup failed: user={}, wallet={}, error={}", user_id, name, e);
+            return Err(not_found());
+        }
+    };
+    let network = network.require_evm()?;
+
+    let init = probe_network(&state.chain_clients, network.as_str(), &address).await;
+    state.storage.initialize_wallet_networks(&name, std::slice::from_ref(&init)).await.map_err(|e| {
+        error!("initializing {} for wallet {} failed: {}", network.as_str(), name, e);
+        ApiError::new(ApiErrorCode::WalletInitializationFailed, "Failed to initialize wallet network")
+    })?;
+    info!("wallet {} initialized on {}: {}", name, network.as_str(), init.status());
+    Ok(Json(NetworkInitStatus::from(&init)))
+}
@@ -0,0 +1,140 @@
+//! wallet允许network集合 handlers
+//!
+//! 集合保存在托管wallet的记录里，由 `WalletManager` 统一执行；这里负责
+//! 读写接口，以及把 `NetworkNotAllowed` 映射为 403 `NETWORK_NOT_ALLOWED`。
+//! 服务端sign、归集和桥接路径在动手前调用 [`check_network_allowed`]。
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use std::sync::Arc;
+use tracing::{error, warn};
+
+use super::wallet_tokens::ADMIN_ISSUER;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::authorize_owner_or_admin;
+use crate::api::middleware::request_metrics::note_wallet_error;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ValidJson, ValidPath, WalletNameParam};
+use crate::core::errors::WalletError;
+use crate::storage::TransactionFilter;
+
+fn network_policy_error(e: WalletError) -> ApiError {
+    let code = match &e {
+        WalletError::NetworkNotAllowed(_) => ApiErrorCode::NetworkNotAllowed,
+        WalletError::NotFoundError(_) => ApiErrorCode::WalletNotFound,
+        _ => {
+            error!("wallet network policy update failed: {}", e);
+            note_wallet_error(&e);
+            return ApiError::new(ApiErrorCode::DbError, "Failed to update allowed networks");
+        }
+    };
+    ApiError::new(code, e.to_string())
+}
+
+/// 拒绝wallet允许集合之外的network；返回所用的允许集合（`None` 为不受限）
+pub(crate) fn check_network_allowed(
+    state: &WalletServer,
+    wallet_name: &str,
+    network: &str,
+) -> Result<Option<Vec<String>>, ApiError> {
+    state.wallet_manager.ensure_network_allowed(wallet_name, network).map_err(network_policy_error)
+}
+
+/// 之前可用、现在不可用的network；之前不受限时以wallet已注册的network为准
+async fn removed_networks(
+    state: &WalletServer,
+    name: &str,
+    previous: Option<&[String]>,
+    allowed: Option<&[String]>,
+) -> Vec<String> {
+    let Some(allowed) = allowed else {
+        return Vec::new();
+    };
+    let mut before: Vec<String> = match previous {
+        Some(previous) => previous.to_vec(),
+        None => {
+            let mut known = match state.wallet_manager.get_wallet_by_name(name).await {
+                Ok(Some(wallet)) => wallet.info.networks.clone(),
+                _ => Vec::new(),
+            };
+            match state.storage.wallet_networks(name).await {
+                Ok(rows) => known.extend(rows.into_iter().map(|r| r.network)),
+                Err(e) => warn!("network lookup for {} failed: {}", name, e),
+            }
+            known
+        }
+    };
+    before.retain(|n| !allowed.contains(n));
+    before.sort();
+    before.dedup();
+    before
+}
+
+/// `GET /api/wallets/:name/networks`：wallet owner 或 admin
+pub async fn get_allowed_networks(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+) -> Result<Json<AllowedNetworksResponse>, ApiError> {
+    let name = name.as_str();
+    authorize_owner_or_admin(&headers, &state, name).await?;
+    let allowed_networks = state.wallet_manager.allowed_networks(name).map_err(network_policy_error)?;
+    Ok(Json(AllowedNetworksResponse { wallet_name: name.to_string(), allowed_networks, warnings: Vec::new() }))
+}
+
+/// `PUT /api/wallets/:name/networks`：wallet owner 或 admin
+///
+/// 移除已有transaction记录的network照常生效，响应中附带警告。
+pub async fn put_allowed_networks(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<AllowedNetworks>,
+) -> Result<Json<AllowedNetworksResponse>, ApiError> {
+    let name = name.as_str();
+    let user_id = authorize_owner_or_admin(&headers, &state, name).await?;
+    let allowed: Option<Vec<String>> =
+        payload.allowed_networks.map(|networks| networks.iter().map(|n| n.as_str().to_string()).collect());
+
+    let previous = state
+        .wallet_manager
+        .set_allowed_networks(name, allowed.clone(), &state.storage)
+        .await
+        .map_err(network_policy_error)?;
+
+    let removed = removed_networks(&state, name, previous.as_deref(), allowed.as_deref()).await;
+    let mut warnings = Vec::new();
+    let wallet_id = match state.storage.wallet_id(name).await {
+        Ok(wallet_id) => wallet_id,
+        Err(e) => {
+            warn!("wallet lookup for {} failed: {}", name, e);
+            None
+        }
+    };
+    for network in removed.iter().filter(|_| wallet_id.is_some()) {
+        let wallet_ids = wallet_id.iter().cloned().collect();
+        let filter = TransactionFilter { wallet_ids, ..Default::default() }.with_network(network);
+        match state.storage.query_transactions(&filter, 0, 1).await {
+            Ok((_, total)) if total > 0 => warnings.push(format!(
+                "{} has {} recorded transaction(s); they stay in the history but the wallet can no longer use {}",
+                network, total, network
+            )),
+            Ok(_) => {}
+            Err(e) => warn!("history lookup for {} on {} failed: {}", name, network, e),
+        }
+    }
+
+    let details = serde_json::json!({
+        "previous": previous,
+        "allowed_networks": allowed,
+        "updated_by": user_id.as_deref().unwrap_or(ADMIN_ISSUER),
+    });
+    if let Err(e) = state.storage.log_action(name, "allowed_networks.updated", &details.to_string(), None, None).await {
+        error!("failed to audit allowed networks change on {}: {}", name, e);
+    }
+    Ok(Json(AllowedNetworksResponse { wallet_name: name.to_string(), allowed_networks: allowed, warnings }))
+}
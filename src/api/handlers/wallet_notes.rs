@@ -0,0 +1,69 @@
+//! wallet描述与元数据 handlers
+//!
+//! 字段保存在 wallets 表上；`PATCH /api/wallets/:name` 以 merge patch 语义更新，
+//! 键数与大小上限针对合并后的结果，由存储层在同一事务内判定。
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use std::sync::Arc;
+use tracing::error;
+
+use super::wallet_tokens::ADMIN_ISSUER;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::authorize_owner_or_admin;
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ParamError, ValidJson, ValidPath, WalletNameParam};
+use crate::storage::{NotesUpdate, MAX_METADATA_BYTES, MAX_METADATA_KEYS};
+
+/// `PATCH /api/wallets/:name`：wallet owner 或 admin
+pub async fn update_wallet_notes(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(patch): ValidJson<WalletNotesPatch>,
+) -> Result<Json<WalletNotesResponse>, ApiError> {
+    let name = name.as_str();
+    let user_id = authorize_owner_or_admin(&headers, &state, name).await?;
+
+    let outcome = state
+        .storage
+        .update_wallet_notes(name, patch.description.clone(), &patch.metadata)
+        .await
+        .map_err(|e| {
+            error!("wallet notes update failed for {}: {}", name, e);
+            ApiError::new(ApiErrorCode::DbError, "Failed to update wallet")
+        })?;
+    let notes = match outcome {
+        NotesUpdate::Updated(notes) => notes,
+        NotesUpdate::NotFound => {
+            return Err(ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found"))
+        }
+        NotesUpdate::TooManyKeys(_) => return Err(ParamError::MetadataKeys(MAX_METADATA_KEYS).into()),
+        NotesUpdate::MetadataTooLarge(size) => {
+            return Err(ParamError::NotesTooLarge(format!(
+                "metadata would be {} bytes, at most {} allowed",
+                size, MAX_METADATA_BYTES
+            ))
+            .into())
+        }
+    };
+
+    // 只记录变更了哪些键，值可能较长
+    let details = serde_json::json!({
+        "description_changed": patch.description.is_some(),
+        "metadata_keys": patch.metadata.keys().collect::<Vec<_>>(),
+        "updated_by": user_id.as_deref().unwrap_or(ADMIN_ISSUER),
+    });
+    if let Err(e) = state.storage.log_action(name, "wallet.notes_updated", &details.to_string(), None, None).await {
+        error!("failed to audit wallet notes change on {}: {}", name, e);
+    }
+    Ok(Json(WalletNotesResponse {
+        wallet_name: name.to_string(),
+        description: notes.description,
+        metadata: notes.metadata,
+    }))
+}
@@ -0,0 +1,177 @@
+//! wallet范围 token 管理 handlers
+//!
+//! 只有wallet owner（会话 token）或 admin（API key，需指定 `owner_user_id`）
+//! 可以签发、列出和吊销 token；wallet范围 token 本身（无论 bearer 还是签名请求）
+//! 不能管理 token。
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use rand::RngCore;
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::extract_user::{authorize_owner_or_admin, extract_token};
+use crate::api::middleware::request_signing::SIGNING_SECRET_PREFIX;
+use crate::api::middleware::request_metrics::note_storage_error;
+use crate::api::middleware::wallet_scope::{verified_key_id, WALLET_TOKEN_PREFIX};
+use crate::api::server::WalletServer;
+use crate::api::types::*;
+use crate::api::validators::{ParamError, ValidJson, ValidPath, WalletNameParam};
+use crate::security::signing_secret::SealedSigningSecret;
+use crate::storage::{NewWalletToken, AUTH_MODE_HMAC};
+
+#[derive(Debug, Deserialize)]
+pub struct TokenOwnerQuery {
+    /// admin 请求需要；owner 请求忽略
+    pub owner_user_id: Option<String>,
+}
+
+/// `created_by` 中代表 API key 调用方
+pub(crate) const ADMIN_ISSUER: &str = "admin";
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    error!("wallet token storage error: {}", e);
+    note_storage_error(&e);
+    ApiError::new(ApiErrorCode::DbError, "Failed to access wallet tokens")
+}
+
+/// 返回 `(owner_user_id, created_by)`
+async fn token_manager(
+    headers: &HeaderMap,
+    state: &Arc<WalletServer>,
+    wallet_name: &str,
+    owner_user_id: Option<String>,
+) -> Result<(String, String), ApiError> {
+    let bearer = extract_token(headers);
+    if bearer.as_deref().is_some_and(|t| t.starts_with(WALLET_TOKEN_PREFIX)) || verified_key_id(headers).is_some() {
+        return Err(ApiError::new(ApiErrorCode::TokenCapabilityDenied, "Forbidden: Wallet tokens cannot manage tokens"));
+    }
+    if let Some(user_id) = authorize_owner_or_admin(headers, state, wallet_name).await? {
+        return Ok((user_id.clone(), user_id));
+    }
+    let owner = owner_user_id.ok_or(ParamError::Missing("owner_user_id"))?;
+    Ok((owner, ADMIN_ISSUER.to_string()))
+}
+
+async fn audit(state: &WalletServer, wallet_name: &str, action: &str, details: serde_json::Value) {
+    if let Err(e) = state.storage.log_action(wallet_name, action, &details.to_string(), None, None).await {
+        error!("failed to audit {} on {}: {}", action, wallet_name, e);
+    }
+}
+
+/// `POST /api/wallets/:name/tokens`
+pub async fn create_wallet_token(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    ValidJson(payload): ValidJson<CreateWalletToken>,
+) -> Result<Json<WalletTokenCreatedResponse>, ApiError> {
+    let name = name.as_str();
+    let (owner, created_by) = token_manager(&headers, &state, name, payload.owner_user_id.clone()).await?;
+
+    let (wallet_id, _) = state
+        .user_db
+        .find_user_wallet(&owner, name)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            ApiError::new(ApiErrorCode::WalletNotFound, "Wallet not found")
+        })?;
+
+    let mut secret = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut secret);
+    let hmac = payload.auth_mode == AUTH_MODE_HMAC;
+    let prefix = if hmac { SIGNING_SECRET_PREFIX } else { WALLET_TOKEN_PREFIX };
+    let token = format!("{}{}", prefix, hex::encode(secret));
+    let id = state.storage.id_generator().new_id();
+    let sealed = if hmac {
+        Some(SealedSigningSecret::seal(&id, &token).map_err(|e| {
+            error!("sealing signing secret for {} failed: {}", name, e);
+            ApiError::new(ApiErrorCode::EncryptionFailed, "Failed to seal signing secret")
+        })?)
+    } else {
+        None
+    };
+    let expires_at = state.storage.clock().now().timestamp() + payload.expires_in_secs as i64;
+
+    state
+        .storage
+        .insert_wallet_token(&NewWalletToken {
+            id: &id,
+            token_hash: &crate::storage::hash_token(&token),
+            wallet_id,
+            wallet_name: name,
+            owner_user_id: &owner,
+            capabilities: payload.capabilities,
+            amount_cap: payload.amount_cap.as_ref().map(|cap| cap.as_str()),
+            expires_at,
+            created_by: &created_by,
+            auth_mode: payload.auth_mode,
+            signing_secret: sealed.as_ref(),
+        })
+        .await
+        .map_err(storage_error)?;
+    let record = state
+        .storage
+        .find_wallet_token_by_id(&id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| storage_error(anyhow::anyhow!("wallet token {} vanished after insert", id)))?;
+
+    audit(
+        &state,
+        name,
+        "wallet_token.created",
+        serde_json::json!({
+            "token_id": id,
+            "created_by": created_by,
+            "capabilities": payload.capabilities,
+            "auth_mode": payload.auth_mode,
+        }),
+    )
+    .await;
+    let (token, signing_secret) = if hmac { (None, Some(token)) } else { (Some(token), None) };
+    Ok(Json(WalletTokenCreatedResponse { token, signing_secret, record }))
+}
+
+/// `GET /api/wallets/:name/tokens`
+pub async fn list_wallet_tokens(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    ValidPath(name): ValidPath<WalletNameParam>,
+    Query(query): Query<TokenOwnerQuery>,
+) -> Result<Json<WalletTokenListResponse>, ApiError> {
+    let name = name.as_str();
+    let (owner, _) = token_manager(&headers, &state, name, query.owner_user_id).await?;
+    let tokens = state.storage.wallet_tokens_for(&owner, name).await.map_err(storage_error)?;
+    Ok(Json(WalletTokenListResponse { tokens }))
+}
+
+/// `DELETE /api/wallets/:name/tokens/:token_id`：立即生效
+pub async fn revoke_wallet_token(
+    State(state): State<Arc<WalletServer>>,
+    headers: HeaderMap,
+    Path((name, token_id)): Path<(String, String)>,
+    Query(query): Query<TokenOwnerQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let name = WalletNameParam::try_from(name.as_str())?;
+    let name = name.as_str();
+    let (owner, revoked_by) = token_manager(&headers, &state, name, query.owner_user_id).await?;
+
+    if !state.storage.revoke_wallet_token(&owner, name, &token_id).await.map_err(storage_error)? {
+        return Err(ApiError::new(ApiErrorCode::TokenNotFound, "Token not found"));
+    }
+    audit(
+        &state,
+        name,
+        "wallet_token.revoked",
+        serde_json::json!({ "token_id": token_id, "revoked_by": revoked_by }),
+    )
+    .await;
+    Ok(Json(serde_json::json!({ "success": true, "message": "Token revoked" })))
+}
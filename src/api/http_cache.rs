@@ -0,0 +1,246 @@
+//! Conditional GETs and a read-through response cache for listings.
+//!
+//! Only routes in [`CACHEABLE_ROUTES`] are ever cached or given an ETag. The
+//! list is explicit on purpose: an endpoint that returns key material
+//! (mnemonic reveal, backup, keystore export) cannot become cacheable by
+//! reusing a helper, and everything not on the list leaves with
+//! `Cache-Control: no-store` (see [`no_store_by_default`]).
+//!
+//! A response is identified by `(route, query, principal)` and stamped with a
+//! version token the handler reads *before* building the body. Tokens come
+//! from counters that writers advance inside their own transactions, so a
+//! token never runs ahead of the data: a write that lands while a body is
+//! being built leaves that body filed under the old token, and the next
+//! request sees the new token and rebuilds. The ETag is a hash of the key and
+//! the token, which lets a client holding a current ETag get a 304 without the
+//! body being built or looked up.
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::types::ApiError;
+
+/// `GET /api/wallets`, versioned by the wallets epoch and the caller's link epoch.
+pub const WALLET_LIST: &str = "GET /api/wallets";
+/// `GET /api/bridge/history`, versioned by the bridge transactions epoch.
+pub const BRIDGE_HISTORY: &str = "GET /api/bridge/history";
+
+/// How long a client may reuse a response of `route` without revalidating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    pub route: &'static str,
+    pub max_age_secs: u32,
+}
+
+/// The only routes that get an ETag and a server-side cache entry.
+pub const CACHEABLE_ROUTES: &[CachePolicy] = &[
+    // a user's own list changes when they act, so keep the reuse window short
+    CachePolicy { route: WALLET_LIST, max_age_secs: 5 },
+    CachePolicy { route: BRIDGE_HISTORY, max_age_secs: 15 },
+];
+
+/// Entries kept before the cache is dropped wholesale.
+const DEFAULT_CAPACITY: usize = 1024;
+/// Larger bodies are served but not kept.
+const MAX_ENTRY_BYTES: usize = 1 << 20;
+
+pub fn policy(route: &str) -> Option<&'static CachePolicy> {
+    CACHEABLE_ROUTES.iter().find(|p| p.route == route)
+}
+
+/// Identity of one cached response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    route: &'static str,
+    params: String,
+    principal: String,
+}
+
+impl CacheKey {
+    /// `params` is the raw query string; `principal` whoever the response
+    /// was authorized for (user id, or a fixed name for the API key).
+    pub fn new(route: &'static str, params: Option<&str>, principal: &str) -> Self {
+        Self { route, params: params.unwrap_or_default().to_string(), principal: principal.to_string() }
+    }
+
+    /// Strong ETag of this response at `version`.
+    pub fn etag(&self, version: &str) -> String {
+        let mut hasher = Sha256::new();
+        for part in [self.route, &self.params, &self.principal, version] {
+            hasher.update((part.len() as u64).to_be_bytes());
+            hasher.update(part.as_bytes());
+        }
+        format!("\"{}\"", hex::encode(&hasher.finalize()[..16]))
+    }
+}
+
+struct Entry {
+    version: String,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+/// Whether an `If-None-Match` header lists `etag` (weak comparison, as
+/// RFC 9110 prescribes for this header).
+fn none_match(request: &HeaderMap, etag: &str) -> bool {
+    request.get_all(header::IF_NONE_MATCH).iter().filter_map(|v| v.to_str().ok()).any(|v| {
+        v.split(',').map(str::trim).any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+    })
+}
+
+fn cache_headers(headers: &mut HeaderMap, policy: &CachePolicy, etag: &str) {
+    if let Ok(v) = HeaderValue::from_str(etag) {
+        headers.insert(header::ETAG, v);
+    }
+    if let Ok(v) = HeaderValue::from_str(&format!("private, max-age={}", policy.max_age_secs)) {
+        headers.insert(header::CACHE_CONTROL, v);
+    }
+}
+
+/// Read-through cache of `200` bodies for the routes in [`CACHEABLE_ROUTES`].
+pub struct ResponseCache {
+    capacity: usize,
+    entries: Mutex<HashMap<CacheKey, Entry>>,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), entries: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Answers a GET for `key` at `version`: `304` when the client already
+    /// holds it, the cached body when one was stored at `version`, otherwise
+    /// whatever `build` returns (kept for next time if it is a `200`). A
+    /// route outside [`CACHEABLE_ROUTES`] always runs `build` and is marked
+    /// `no-store`.
+    pub async fn respond<F, Fut>(
+        &self,
+        request: &HeaderMap,
+        key: CacheKey,
+        version: &str,
+        build: F,
+    ) -> Result<Response, ApiError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Response, ApiError>>,
+    {
+        let Some(policy) = policy(key.route) else {
+            let mut response = build().await?;
+            response.headers_mut().insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+            return Ok(response);
+        };
+
+        let etag = key.etag(version);
+        if none_match(request, &etag) {
+            let mut response = StatusCode::NOT_MODIFIED.into_response();
+            cache_headers(response.headers_mut(), policy, &etag);
+            return Ok(response);
+        }
+        let hit = {
+            let entries = self.entries.lock();
+            entries.get(&key).filter(|e| e.version == version).map(|e| (e.headers.clone(), e.body.clone()))
+        };
+        if let Some((headers, body)) = hit {
+            let mut response = Response::new(Body::from(body));
+            *response.headers_mut() = headers;
+            cache_headers(response.headers_mut(), policy, &etag);
+            return Ok(response);
+        }
+
+        let response = build().await?;
+        if response.status() != StatusCode::OK {
+            return Ok(response);
+        }
+        let (mut parts, body) = response.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX).await.map_err(|e| {
+            tracing::error!("failed to buffer {} response: {}", key.route, e);
+            ApiError::new(ApiErrorCode::InternalError, "Failed to build response")
+        })?;
+        if body.len() <= MAX_ENTRY_BYTES {
+            let entry = Entry {
+                version: version.to_string(),
+                headers: parts.headers.clone(),
+                body: body.clone(),
+            };
+            let mut entries = self.entries.lock();
+            if entries.len() >= self.capacity && !entries.contains_key(&key) {
+                entries.clear();
+            }
+            entries.insert(key, entry);
+        }
+        cache_headers(&mut parts.headers, policy, &etag);
+        Ok(Response::from_parts(parts, Body::from(body)))
+    }
+}
+
+/// Marks every response that did not choose its own caching as `no-store`.
+pub async fn no_store_by_default(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    if !response.headers().contains_key(header::CACHE_CONTROL) {
+        response.headers_mut().insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(body: &'static str) -> Result<Response, ApiError> {
+        Ok(body.into_response())
+    }
+
+    #[tokio::test]
+    async fn test_routes_outside_the_list_are_never_cached() {
+        let cache = ResponseCache::default();
+        let mut request = HeaderMap::new();
+        let key = CacheKey::new("GET /api/wallets/:name/backup", None, "admin");
+        request.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&key.etag("1")).unwrap());
+
+        let response = cache.respond(&request, key, "1", || async { ok("secret") }).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::ETAG).is_none());
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_entries_are_served_only_at_their_version() {
+        let cache = ResponseCache::default();
+        let key = || CacheKey::new(WALLET_LIST, Some("limit=5"), "u1");
+        let headers = HeaderMap::new();
+        cache.respond(&headers, key(), "1", || async { ok("first") }).await.unwrap();
+
+        let hit = cache.respond(&headers, key(), "1", || async { ok("rebuilt") }).await.unwrap();
+        assert_eq!(axum::body::to_bytes(hit.into_body(), usize::MAX).await.unwrap(), "first");
+        let miss = cache.respond(&headers, key(), "2", || async { ok("rebuilt") }).await.unwrap();
+        assert_eq!(miss.headers()[header::ETAG], key().etag("2").as_str());
+        assert_eq!(axum::body::to_bytes(miss.into_body(), usize::MAX).await.unwrap(), "rebuilt");
+        assert_ne!(key().etag("1"), CacheKey::new(WALLET_LIST, Some("limit=5"), "u2").etag("1"));
+    }
+}
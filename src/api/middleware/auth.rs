@@ -1,10 +1,17 @@
 //! 认证中间件
 //! 
-//! 提供API请求认证功能
+//! 提供API请求认证功能。管理 API 密钥可以有两把（主密钥与第二把），轮换
+//! 重叠期间两把都有效，见 [`ApiKeyRing`]。
 
 use axum::http::{HeaderMap, StatusCode};
+use parking_lot::RwLock;
+use rand::RngCore;
+use std::sync::Arc;
 use subtle::ConstantTimeEq;
 use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::security::{ApiKeys, SecretVec};
 
 /// 常量时间比较（防止时序攻击）
 fn constant_time_eq_hash(a: &[u8], b: &[u8]) -> bool {
@@ -14,52 +21,159 @@ fn constant_time_eq_hash(a: &[u8], b: &[u8]) -> bool {
     ha.ct_eq(&hb).into()
 }
 
+/// 管理 API 密钥的当前状态
+///
+/// clone 共享同一份密钥，轮换对所有持有者立即生效。没有配置密钥时不做认证
+/// （与之前 `api_key: None` 相同）。
+#[derive(Clone, Default)]
+pub struct ApiKeyRing {
+    keys: Arc<RwLock<Option<ApiKeys>>>,
+    /// 串行化轮换：从 [`Self::next_rotation`] 读密钥到 [`Self::replace`] 之间一直持有
+    rotation: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl From<Option<SecretVec>> for ApiKeyRing {
+    fn from(primary: Option<SecretVec>) -> Self {
+        Self::new(primary.map(|primary| ApiKeys { primary, secondary: None }))
+    }
+}
+
+impl ApiKeyRing {
+    pub fn new(keys: Option<ApiKeys>) -> Self {
+        Self { keys: Arc::new(RwLock::new(keys)), rotation: Arc::default() }
+    }
+
+    /// 轮换锁。并发的两次轮换若都从同一份密钥算起，后完成的会覆盖先完成的，
+    /// 已经返回给调用方的第二把密钥就不会生效
+    pub async fn lock_rotation(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.rotation.lock().await
+    }
+
+    /// 没有配置密钥，认证关闭
+    pub fn is_none(&self) -> bool {
+        self.keys.read().is_none()
+    }
+
+    /// 设置第二把密钥；没有主密钥时返回 false（不能只靠第二把开启认证）
+    pub fn set_secondary(&self, secondary: SecretVec) -> bool {
+        match self.keys.write().as_mut() {
+            Some(keys) => {
+                keys.secondary = Some(secondary);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// `provided` 是否等于任一把密钥。两把都比较，耗时与匹配哪一把无关
+    pub fn accepts(&self, provided: &[u8]) -> bool {
+        let keys = self.keys.read();
+        let Some(keys) = keys.as_ref() else { return false };
+        let primary = constant_time_eq_hash(provided, &keys.primary);
+        let secondary = keys.secondary.as_ref().is_some_and(|secondary| constant_time_eq_hash(provided, secondary));
+        primary | secondary
+    }
+
+    /// 第二把升为主密钥并生成新的第二把。没有第二把时主密钥保留，因此旧主
+    /// 密钥最晚在第二次轮换后失效。没有配置密钥时返回 `None`。
+    pub fn rotate(&self) -> Option<RotatedApiKey> {
+        let (keys, rotated) = self.next_rotation()?;
+        self.replace(keys);
+        Some(rotated)
+    }
+
+    /// [`Self::rotate`] 会换上的密钥，当前密钥不变；先写入密钥后端再
+    /// [`Self::replace`]，保存失败时旧密钥继续有效。调用方持有 [`Self::lock_rotation`]
+    pub fn next_rotation(&self) -> Option<(ApiKeys, RotatedApiKey)> {
+        let mut keys = self.keys.read().clone()?;
+        let mut bytes = Zeroizing::new([0u8; 32]);
+        rand::rngs::OsRng.fill_bytes(&mut bytes[..]);
+        let secondary = Zeroizing::new(hex::encode(&bytes[..]));
+        if let Some(previous) = keys.secondary.take() {
+            keys.primary = previous;
+        }
+        keys.secondary = Some(Zeroizing::new(secondary.as_bytes().to_vec()));
+        let rotated = RotatedApiKey {
+            primary_fingerprint: fingerprint(&keys.primary),
+            secondary_fingerprint: fingerprint(secondary.as_bytes()),
+            secondary,
+        };
+        Some((keys, rotated))
+    }
+
+    /// 换成 `keys`（轮换，或密钥后端里的密钥变了）
+    pub fn replace(&self, keys: ApiKeys) {
+        *self.keys.write() = Some(keys);
+    }
+
+    /// 与 `keys` 是否相同
+    pub fn holds(&self, keys: &ApiKeys) -> bool {
+        self.keys.read().as_ref().is_some_and(|current| {
+            current.primary == keys.primary && current.secondary == keys.secondary
+        })
+    }
+}
+
+/// 一次轮换的结果；新密钥只在这里出现一次
+pub struct RotatedApiKey {
+    pub secondary: Zeroizing<String>,
+    /// 两把密钥的指纹（SHA-256 前 8 字节的十六进制），供审计日志区分密钥
+    pub primary_fingerprint: String,
+    pub secondary_fingerprint: String,
+}
+
+fn fingerprint(key: &[u8]) -> String {
+    hex::encode(&Sha256::digest(key)[..8])
+}
+
+/// 请求头里的密钥（`X-API-KEY` 或 `Authorization`）是否被接受
+fn provided_key_accepted(headers: &HeaderMap, api_key: &ApiKeyRing) -> Result<bool, StatusCode> {
+    // 优先checkX-API-KEY头（标准API密钥方式）
+    if let Some(provided) = headers.get("X-API-KEY").or_else(|| headers.get("x-api-key")) {
+        let provided = provided.to_str()
+            .map_err(|_| {
+                tracing::warn!("Invalid UTF-8 in X-API-KEY header");
+                StatusCode::BAD_REQUEST
+            })?;
+        
+        if api_key.accepts(provided.trim().as_bytes()) {
+            return Ok(true);
+        }
+    }
+    
+    // 回退checkAuthorization头（兼容JWT或旧实现）
+    if let Some(provided) = headers.get("Authorization") {
+        let provided = provided.to_str()
+            .map_err(|_| {
+                tracing::warn!("Invalid UTF-8 in Authorization header");
+                StatusCode::BAD_REQUEST
+            })?;
+        
+        // 支持 Bearer token 和直接 API key 两种格式
+        let key = if provided.starts_with("Bearer ") {
+            provided.trim_start_matches("Bearer ").trim()
+        } else {
+            provided.trim()
+        };
+        if api_key.accepts(key.as_bytes()) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
 /// 开发模式下的宽松认证
 /// 
 /// 在开发环境(DEV_MODE=1)下，如果没有提供认证头，则允许访问
 pub async fn authenticate_with_dev_mode(
     headers: &HeaderMap,
-    api_key: &Option<crate::security::SecretVec>,
+    api_key: &ApiKeyRing,
 ) -> Result<(), StatusCode> {
     let is_dev = std::env::var("DEV_MODE").ok().as_deref() == Some("1");
     
-    if let Some(expected) = api_key {
-        // 优先checkX-API-KEY头（标准API密钥方式）
-        if let Some(provided) = headers.get("X-API-KEY").or_else(|| headers.get("x-api-key")) {
-            let provided = provided.to_str()
-                .map_err(|_| {
-                    tracing::warn!("Invalid UTF-8 in X-API-KEY header");
-                    StatusCode::BAD_REQUEST
-                })?;
-            
-            let pbytes = provided.trim().as_bytes();
-            let ebytes = &**expected;
-            
-            if constant_time_eq_hash(pbytes, ebytes) {
-                return Ok(());
-            }
-        }
-        
-        // 回退checkAuthorization头（兼容JWT或旧实现）
-        if let Some(provided) = headers.get("Authorization") {
-            let provided = provided.to_str()
-                .map_err(|_| {
-                    tracing::warn!("Invalid UTF-8 in Authorization header");
-                    StatusCode::BAD_REQUEST
-                })?;
-            
-            // 支持 Bearer token 和直接 API key 两种格式
-            let key = if provided.starts_with("Bearer ") {
-                provided.trim_start_matches("Bearer ").trim()
-            } else {
-                provided.trim()
-            };
-            let pbytes = key.as_bytes();
-            let ebytes = &**expected;
-            
-            if constant_time_eq_hash(pbytes, ebytes) {
-                return Ok(());
-            }
+    if !api_key.is_none() {
+        if provided_key_accepted(headers, api_key)? {
+            return Ok(());
         }
         
         // 开发模式：如果没有提供任何认证头，也允许访问
@@ -73,10 +187,19 @@ pub async fn authenticate_with_dev_mode(
     Ok(())
 }
 
+/// 必须带一把有效的密钥，DEV_MODE 与没有配置密钥都不放行；用于密钥轮换这类管理操作
+pub async fn require_api_key(headers: &HeaderMap, api_key: &ApiKeyRing) -> Result<(), StatusCode> {
+    if provided_key_accepted(headers, api_key)? {
+        Ok(())
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
 /// 严格认证（向后兼容）
 pub async fn authenticate(
     headers: &HeaderMap,
-    api_key: &Option<crate::security::SecretVec>,
+    api_key: &ApiKeyRing,
 ) -> Result<(), StatusCode> {
     authenticate_with_dev_mode(headers, api_key).await
 }
@@ -19,6 +19,8 @@ use rand::RngCore;
 use sha2::{Sha256, Digest};
 use tracing::{debug, warn};
 
+use crate::ops::incidents::spawn_named;
+
 /// CSRF Token管理器
 #[derive(Clone)]
 pub struct CsrfProtection {
@@ -131,7 +133,7 @@ impl CsrfProtection {
     
     /// 定期清理任务
     pub async fn start_cleanup_task(self: Arc<Self>) {
-        tokio::spawn(async move {
+        spawn_named("csrf_cleanup", async move {
             let mut interval = tokio::time::interval(Duration::from_secs(300)); // 5 minutes
             loop {
                 interval.tick().await;
@@ -1,24 +1,18 @@
 //! user提取中间件 - from请求头中提取当前User ID
 
-use axum::http::{HeaderMap, StatusCode};
-use axum::response::Json;
+use axum::http::HeaderMap;
 use std::sync::Arc;
 
+use crate::api::error_catalog::ApiErrorCode;
 use crate::api::server::WalletServer;
-use crate::api::types::ErrorResponse;
+use crate::api::types::ApiError;
 
 /// from Authorization header 提取 token
 pub fn extract_token(headers: &HeaderMap) -> Option<String> {
     headers
         .get("Authorization")
         .and_then(|v| v.to_str().ok())
-        .map(|v| {
-            if v.starts_with("Bearer ") {
-                v[7..].to_string()
-            } else {
-                v.to_string()
-            }
-        })
+        .map(|v| v.strip_prefix("Bearer ").unwrap_or(v).to_string())
 }
 
 /// from请求头提取当前User ID（通过validatetoken）
@@ -27,16 +21,10 @@ pub fn extract_token(headers: &HeaderMap) -> Option<String> {
 pub async fn extract_user_id_from_token(
     headers: &HeaderMap,
     state: &Arc<WalletServer>,
-) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<String, ApiError> {
     // 1. 提取token
     let token = extract_token(headers).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Unauthorized: Authentication token is required".to_string(), // ✅ 英文
-                code: "AUTH_REQUIRED".to_string(),
-            }),
-        )
+        ApiError::new(ApiErrorCode::AuthRequired, "Unauthorized: Authentication token is required")
     })?;
     
     // 2. ✅ fromSessionStorevalidatetoken并fetchuser_id
@@ -47,13 +35,7 @@ pub async fn extract_user_id_from_token(
         }
         Err(e) => {
             tracing::warn!("❌ Tokenvalidatefailed: {}", e);
-            Err((
-                StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: format!("Unauthorized: Token validation failed - {}", e), // ✅ 英文
-                    code: "INVALID_TOKEN".to_string(),
-                }),
-            ))
+            Err(ApiError::new(ApiErrorCode::InvalidToken, format!("Unauthorized: Token validation failed - {}", e)))
         }
     }
 }
@@ -63,31 +45,41 @@ pub async fn verify_wallet_ownership(
     user_id: &str,
     wallet_name: &str,
     state: &Arc<WalletServer>,
-) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+) -> Result<(), ApiError> {
     // queryuser的wallet列表
     let user_wallets = state.user_db.get_user_wallets(user_id)
         .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: format!("Failed to query user wallets: {}", e), // ✅ 英文
-                    code: "DB_ERROR".to_string(),
-                }),
-            )
-        })?;
+        .map_err(|e| ApiError::new(ApiErrorCode::DbError, format!("Failed to query user wallets: {}", e)))?;
     
     // checkwallet是否属于user
     if !user_wallets.contains(&wallet_name.to_string()) {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ErrorResponse {
-                error: "Forbidden: You don't have permission to access this wallet".to_string(), // ✅ 英文
-                code: "WALLET_ACCESS_DENIED".to_string(),
-            }),
+        return Err(ApiError::new(
+            ApiErrorCode::WalletAccessDenied,
+            "Forbidden: You don't have permission to access this wallet",
         ));
     }
     
     Ok(())
 }
 
+
+/// wallet owner（会话 token）或 admin（API key）
+///
+/// 携带有效会话 token 时必须是wallet owner；否则按 API key 认证。
+/// 返回 owner 的 User ID，admin 返回 `None`。
+pub async fn authorize_owner_or_admin(
+    headers: &HeaderMap,
+    state: &Arc<WalletServer>,
+    wallet_name: &str,
+) -> Result<Option<String>, ApiError> {
+    if let Some(token) = extract_token(headers) {
+        if let Ok(user_id) = state.session_store.validate_token(&token).await {
+            verify_wallet_ownership(&user_id, wallet_name, state).await?;
+            return Ok(Some(user_id));
+        }
+    }
+    super::authenticate(headers, &state.api_key)
+        .await
+        .map_err(|_| ApiError::new(ApiErrorCode::AuthFailed, "Unauthorized"))?;
+    Ok(None)
+}
@@ -1,4 +1,8 @@
 pub mod auth;
 pub mod extract_user;
+pub mod request_id;
+pub mod request_metrics;
+pub mod request_signing;
+pub mod wallet_scope;
 
-pub use auth::{authenticate, authenticate_with_dev_mode};
+pub use auth::{authenticate, authenticate_with_dev_mode, require_api_key, ApiKeyRing};
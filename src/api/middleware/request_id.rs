@@ -0,0 +1,74 @@
+//! 请求 ID
+//!
+//! 每个请求带一个 `X-Request-Id`：客户端给出的值只要是 1-64 个字母、数字、
+//! `-`、`_` 或 `.` 就沿用，否则生成 UUID v4。响应原样回带该头，
+//! [`ApiError`](crate::api::types::ApiError) 的 `error.request_id` 也取这个值，
+//! 方便把客户端看到的错误与服务端日志对上。
+//!
+//! 当前值放在 task-local 里，只在本中间件包裹的请求内可见；在别处调用
+//! [`current_request_id`] 返回 `None`。
+
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+fn is_acceptable(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+/// 当前请求的 ID；不在 [`assign_request_id`] 包裹的请求内时为 `None`
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+pub async fn assign_request_id(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| is_acceptable(v))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    // 只含可见 ASCII，必然是合法的头部值
+    let value = HeaderValue::from_str(&id).expect("request id is a valid header value");
+    request.headers_mut().insert(REQUEST_ID_HEADER, value.clone());
+
+    let mut response = REQUEST_ID.scope(id, next.run(request)).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_incoming_ids_are_filtered() {
+        assert!(is_acceptable("3f2b9c1e-8d4a-4b7e-9a61-0c2d5e7f8a90"));
+        assert!(is_acceptable("req_42.retry"));
+        assert!(!is_acceptable(""));
+        assert!(!is_acceptable("has space"));
+        assert!(!is_acceptable("line\nbreak"));
+        assert!(!is_acceptable(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[tokio::test]
+    async fn test_current_id_only_inside_scope() {
+        assert_eq!(current_request_id(), None);
+        let seen = REQUEST_ID.scope("abc".to_string(), async { current_request_id() }).await;
+        assert_eq!(seen.as_deref(), Some("abc"));
+    }
+}
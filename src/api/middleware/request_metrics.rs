@@ -0,0 +1,141 @@
+//! 请求指标与错误分类
+//!
+//! 每个响应计入 `http_requests_total{status_class, error_class}` 与
+//! `response_time_by_route_seconds{route}`（路由模板；未匹配为 `unmatched`）。4xx/5xx 的 `error_class` 优先取 handler 在构造错误
+//! 响应时通过 [`note_error_class`] / [`note_wallet_error`] / [`note_error`]
+//! 记下的分类，没有记录时按状态码推断（[`ErrorClass::from_status`]）；2xx/3xx
+//! 为 `none`。错误响应同时计入实例的
+//! [`ErrorTally`](crate::ops::incidents::ErrorTally)（`GET /api/admin/incidents`），
+//! 5xx 另外写一条 `request.failed` 到事件日志，只含方法、路由模板、状态码与
+//! 分类，不含错误消息和实际 path 参数。
+//!
+//! 分类记录在 task-local 里，只在本中间件包裹的请求内生效；在别处调用
+//! `note_*` 什么也不做。
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use std::cell::Cell;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::api::server::WalletServer;
+use crate::core::error_class::{classify_or, classify_wallet_error, ErrorClass};
+use crate::core::errors::WalletError;
+use crate::ops::incidents::spawn_named;
+use crate::storage::{journal_events, NewJournalEvent};
+
+tokio::task_local! {
+    static NOTED_CLASS: Cell<Option<ErrorClass>>;
+}
+
+/// 没有错误的响应的 `error_class` 标签
+pub const NO_ERROR_CLASS: &str = "none";
+
+/// 没有匹配到路由的请求的 `route` 标签
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// 记下当前请求的错误分类；多次调用以最后一次为准
+pub fn note_error_class(class: ErrorClass) {
+    let _ = NOTED_CLASS.try_with(|noted| noted.set(Some(class)));
+}
+
+pub fn note_wallet_error(e: &WalletError) {
+    note_error_class(classify_wallet_error(e));
+}
+
+/// `fallback`：错误链中没有可识别的类型时使用（存储层错误多为纯文本，传 `Database`）
+pub fn note_error(e: &anyhow::Error, fallback: ErrorClass) {
+    note_error_class(classify_or(e, fallback));
+}
+
+/// 存储层返回的错误：链中没有更具体的类型时记为 `database`
+pub fn note_storage_error(e: &anyhow::Error) {
+    note_error(e, ErrorClass::Database);
+}
+
+fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+/// 响应的错误分类；小于 400 时为 `None`
+pub fn response_error_class(status: StatusCode, noted: Option<ErrorClass>) -> Option<ErrorClass> {
+    (status.is_client_error() || status.is_server_error())
+        .then(|| noted.unwrap_or_else(|| ErrorClass::from_status(status.as_u16())))
+}
+
+pub async fn record_request_metrics(State(state): State<Arc<WalletServer>>, req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = req.method().clone();
+    let route = req.extensions().get::<MatchedPath>().map_or(UNMATCHED_ROUTE, MatchedPath::as_str).to_string();
+
+    let (response, noted) = NOTED_CLASS
+        .scope(Cell::new(None), async {
+            let response = next.run(req).await;
+            (response, NOTED_CLASS.with(Cell::get))
+        })
+        .await;
+
+    let status = response.status();
+    let class = response_error_class(status, noted);
+    state.metrics.record_http_request(
+        status_class(status),
+        class.map_or(NO_ERROR_CLASS, ErrorClass::as_str),
+        &route,
+        started.elapsed().as_secs_f64(),
+    );
+    let Some(class) = class else { return response };
+    state.errors.record(class, chrono::Utc::now().timestamp());
+
+    if status.is_server_error() {
+        // 不阻塞响应；写失败（往往正是数据库出了问题）只记日志
+        let storage = state.storage.clone();
+        spawn_named("request_failed_journal", async move {
+            let event = NewJournalEvent {
+                event_type: journal_events::REQUEST_FAILED,
+                entity_type: "route",
+                entity_id: &route,
+                payload: serde_json::json!({
+                    "method": method.as_str(),
+                    "route": &route,
+                    "status": status.as_u16(),
+                    "error_class": class,
+                }),
+            };
+            if let Err(e) = storage.record_event(&event).await {
+                tracing::warn!("failed to journal {} {} ({}): {}", method, route, status.as_u16(), e);
+            }
+        });
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_noted_class_wins_over_status() {
+        let noted = NOTED_CLASS
+            .scope(Cell::new(None), async {
+                note_wallet_error(&WalletError::StorageError("disk full".into()));
+                NOTED_CLASS.with(Cell::get)
+            })
+            .await;
+        assert_eq!(noted, Some(ErrorClass::Database));
+        assert_eq!(response_error_class(StatusCode::INTERNAL_SERVER_ERROR, noted), Some(ErrorClass::Database));
+        assert_eq!(response_error_class(StatusCode::NOT_FOUND, None), Some(ErrorClass::NotFound));
+        assert_eq!(response_error_class(StatusCode::OK, noted), None);
+        // 中间件之外调用不会 panic
+        note_error_class(ErrorClass::Internal);
+    }
+}
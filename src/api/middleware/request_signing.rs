@@ -0,0 +1,383 @@
+//! HMAC 请求签名认证
+//!
+//! `auth_mode = "hmac"` 的wallet范围 token 用 `X-Key-Id`/`X-Timestamp`/`X-Nonce`/`X-Signature`
+//! 四个头签名请求而不传 secret；canonical 串格式见 `docs/REQUEST_SIGNING.md`。
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Request, State},
+    http::{request::Parts, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::api::server::WalletServer;
+use crate::api::server_config::MAX_BODY_SIZE;
+use crate::api::types::ApiError;
+use crate::storage::WalletStorage;
+
+pub const HEADER_KEY_ID: &str = "x-key-id";
+pub const HEADER_TIMESTAMP: &str = "x-timestamp";
+pub const HEADER_NONCE: &str = "x-nonce";
+pub const HEADER_SIGNATURE: &str = "x-signature";
+
+/// validate通过的签名 key id；只由中间件写入
+pub const VERIFIED_KEY_HEADER: &str = "x-verified-key-id";
+
+/// HMAC 签名 secret 的前缀（与 bearer token 的 `wtk_` 区分）
+pub const SIGNING_SECRET_PREFIX: &str = "wsk_";
+
+/// `X-Timestamp` 与服务器时间允许的偏差（秒）
+pub const SIGNATURE_WINDOW_SECS: i64 = 300;
+
+const MAX_NONCE_LEN: usize = 64;
+/// 本地 nonce 缓存条目上限
+const DEFAULT_NONCE_CACHE_CAPACITY: usize = 10_000;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 是否为签名请求（带 `X-Key-Id` 或 `X-Signature`）
+pub fn is_signed(headers: &HeaderMap) -> bool {
+    headers.contains_key(HEADER_KEY_ID) || headers.contains_key(HEADER_SIGNATURE)
+}
+
+/// 见模块文档
+pub fn canonical_query(query: &str) -> String {
+    let mut pairs: Vec<(Vec<u8>, Vec<u8>)> = query
+        .split('&')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            let (name, value) = segment.split_once('=').unwrap_or((segment, ""));
+            (form_decode(name), form_decode(value))
+        })
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{}={}", rfc3986_encode(name), rfc3986_encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// 见模块文档；`query` 为 `?` 之后的原始部分
+pub fn canonical_request(
+    method: &str,
+    path: &str,
+    query: Option<&str>,
+    timestamp: &str,
+    nonce: &str,
+    body: &[u8],
+) -> String {
+    [
+        method.to_ascii_uppercase(),
+        path.to_string(),
+        canonical_query(query.unwrap_or("")),
+        timestamp.to_string(),
+        nonce.to_string(),
+        hex::encode(Sha256::digest(body)),
+    ]
+    .join("\n")
+}
+
+/// `X-Signature` 的值
+pub fn sign(secret: &str, canonical: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(canonical.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// 常量时间比较
+fn signature_matches(secret: &str, canonical: &str, signature: &str) -> bool {
+    let Ok(provided) = hex::decode(signature) else {
+        return false;
+    };
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(canonical.as_bytes());
+    mac.verify_slice(&provided).is_ok()
+}
+
+fn form_decode(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        out.push(high << 4 | low);
+                        i += 2;
+                    }
+                    _ => out.push(b'%'),
+                }
+            }
+            other => out.push(other),
+        }
+        i += 1;
+    }
+    out
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|d| d as u8)
+}
+
+fn rfc3986_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// 已用过的 nonce
+///
+/// 数据库是唯一依据，多个实例共享；本地只缓存最近见过的 nonce，让打到同一
+/// 实例的重放不必再写库。缓存满时先丢弃过期条目，并顺带清理库中过期的行。
+pub struct NonceCache {
+    storage: Arc<WalletStorage>,
+    recent: Mutex<HashMap<(String, String), i64>>,
+    capacity: usize,
+}
+
+impl NonceCache {
+    pub fn new(storage: Arc<WalletStorage>) -> Self {
+        Self { storage, recent: Mutex::new(HashMap::new()), capacity: DEFAULT_NONCE_CACHE_CAPACITY }
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// 记录 `nonce`；已被使用过返回 `false`
+    ///
+    /// 时间戳最多比 `now` 晚一个窗口，而一个时间戳在其后一个窗口内都会被接受，
+    /// 所以 nonce 保留两个窗口。
+    pub async fn claim(&self, key_id: &str, nonce: &str, now: i64) -> anyhow::Result<bool> {
+        let entry = (key_id.to_string(), nonce.to_string());
+        if self.recent.lock().unwrap().get(&entry).is_some_and(|&expires_at| expires_at > now) {
+            return Ok(false);
+        }
+        let expires_at = now + 2 * SIGNATURE_WINDOW_SECS;
+        let claimed = self.storage.claim_request_nonce(key_id, nonce, expires_at, now).await?;
+
+        let purge = {
+            let mut recent = self.recent.lock().unwrap();
+            let full = recent.len() >= self.capacity;
+            if full {
+                recent.retain(|_, expires_at| *expires_at > now);
+                if recent.len() >= self.capacity {
+                    recent.clear();
+                }
+            }
+            recent.insert(entry, expires_at);
+            full
+        };
+        if purge {
+            if let Err(e) = self.storage.purge_request_nonces(now).await {
+                tracing::warn!("failed to purge expired request nonces: {}", e);
+            }
+        }
+        Ok(claimed)
+    }
+}
+
+/// 签名validatefailed的原因
+#[derive(Debug, thiserror::Error)]
+pub enum SignatureError {
+    #[error("Unauthorized: Missing {0} header")]
+    MissingHeader(&'static str),
+    #[error("Unauthorized: Malformed {0} header")]
+    MalformedHeader(&'static str),
+    #[error("Unauthorized: Unknown or revoked signing key")]
+    UnknownKey,
+    #[error("Unauthorized: Token has expired")]
+    KeyExpired,
+    #[error("Unauthorized: Request timestamp is outside the allowed window")]
+    ClockSkew,
+    #[error("Unauthorized: Signature does not match")]
+    BadSignature,
+    #[error("Unauthorized: Nonce has already been used")]
+    Replayed,
+    #[error("Request body too large")]
+    BodyTooLarge,
+    #[error("Failed to validate signature")]
+    Storage(#[from] anyhow::Error),
+}
+
+impl SignatureError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            SignatureError::MissingHeader(_) | SignatureError::MalformedHeader(_) | SignatureError::BadSignature => {
+                "INVALID_SIGNATURE"
+            }
+            SignatureError::UnknownKey => "INVALID_TOKEN",
+            SignatureError::KeyExpired => "TOKEN_EXPIRED",
+            SignatureError::ClockSkew => "SIGNATURE_EXPIRED",
+            SignatureError::Replayed => "NONCE_REPLAYED",
+            SignatureError::BodyTooLarge => "PAYLOAD_TOO_LARGE",
+            SignatureError::Storage(_) => "DB_ERROR",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            SignatureError::BodyTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            SignatureError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+impl IntoResponse for SignatureError {
+    fn into_response(self) -> Response {
+        if let SignatureError::Storage(e) = &self {
+            tracing::error!("request signature validation failed: {}", e);
+        }
+        ApiError::from_code(self.status(), self.code(), self.to_string())
+            .into_response()
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &'static str) -> Result<&'a str, SignatureError> {
+    headers
+        .get(name)
+        .ok_or(SignatureError::MissingHeader(name))?
+        .to_str()
+        .map(str::trim)
+        .map_err(|_| SignatureError::MalformedHeader(name))
+}
+
+/// validate签名请求，返回 key id
+///
+/// 先查时间戳和签名，最后才占用 nonce，签名错误的请求不会消耗 nonce。
+pub async fn verify_signature(
+    state: &WalletServer,
+    parts: &Parts,
+    body: &[u8],
+) -> Result<String, SignatureError> {
+    let key_id = header(&parts.headers, HEADER_KEY_ID)?;
+    let timestamp = header(&parts.headers, HEADER_TIMESTAMP)?;
+    let nonce = header(&parts.headers, HEADER_NONCE)?;
+    let signature = header(&parts.headers, HEADER_SIGNATURE)?;
+    if nonce.is_empty() || nonce.len() > MAX_NONCE_LEN || !nonce.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err(SignatureError::MalformedHeader(HEADER_NONCE));
+    }
+    let signed_at: i64 = timestamp.parse().map_err(|_| SignatureError::MalformedHeader(HEADER_TIMESTAMP))?;
+
+    let now = state.storage.clock().now().timestamp();
+    if (now - signed_at).abs() > SIGNATURE_WINDOW_SECS {
+        return Err(SignatureError::ClockSkew);
+    }
+
+    let (record, sealed) = state.storage.find_signing_key(key_id).await?.ok_or(SignatureError::UnknownKey)?;
+    if record.is_expired(now) {
+        return Err(SignatureError::KeyExpired);
+    }
+    // 信封 KEK 轮换后旧 secret 无法解封，该 key 只能重新签发
+    let secret = sealed.open(&record.id).map_err(|e| {
+        tracing::warn!("signing secret of {} does not open: {}", record.id, e);
+        SignatureError::UnknownKey
+    })?;
+    let canonical =
+        canonical_request(parts.method.as_str(), parts.uri.path(), parts.uri.query(), timestamp, nonce, body);
+    if !signature_matches(&secret, &canonical, signature) {
+        return Err(SignatureError::BadSignature);
+    }
+    if !state.request_nonces.claim(&record.id, nonce, now).await? {
+        return Err(SignatureError::Replayed);
+    }
+    Ok(record.id)
+}
+
+/// 签名请求的认证层；未签名的请求原样放行
+pub async fn verify_signed_requests(
+    State(state): State<Arc<WalletServer>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    request.headers_mut().remove(VERIFIED_KEY_HEADER);
+    if !is_signed(request.headers()) {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let body: Bytes = match axum::body::to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(body) => body,
+        Err(_) => return SignatureError::BodyTooLarge.into_response(),
+    };
+    let key_id = match verify_signature(&state, &parts, &body).await {
+        Ok(key_id) => key_id,
+        Err(e) => return e.into_response(),
+    };
+    let Ok(value) = HeaderValue::from_str(&key_id) else {
+        return SignatureError::MalformedHeader(HEADER_KEY_ID).into_response();
+    };
+    parts.headers.insert(HeaderName::from_static(VERIFIED_KEY_HEADER), value);
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "wsk_0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn test_canonical_query_sorts_and_reencodes() {
+        assert_eq!(canonical_query(""), "");
+        assert_eq!(canonical_query("b=2&a=1"), "a=1&b=2");
+        // 同名参数按值排序，全部保留
+        assert_eq!(canonical_query("tag=z&tag=a&tag=m"), "tag=a&tag=m&tag=z");
+        // `+` 是空格；小写转义统一为大写；不需要转义的字符解码
+        assert_eq!(canonical_query("q=a+b&r=%2f&s=%7E"), "q=a%20b&r=%2F&s=~");
+        // 空段丢弃，缺少 `=` 的参数值为空
+        assert_eq!(canonical_query("&flag&&x="), "flag=&x=");
+        // 不合法的转义按字面保留
+        assert_eq!(canonical_query("v=100%&w=%zz"), "v=100%25&w=%25zz");
+        assert_eq!(canonical_query("name=%E4%B8%AD"), "name=%E4%B8%AD");
+    }
+
+    #[test]
+    fn test_get_with_query_vector() {
+        let canonical = canonical_request(
+            "get",
+            "/api/wallets/treasury/balance",
+            Some("network=eth&token=0xAbC&limit=10"),
+            "1700000000",
+            "n-1",
+            b"",
+        );
+        assert_eq!(
+            canonical,
+            "GET\n/api/wallets/treasury/balance\nlimit=10&network=eth&token=0xAbC\n1700000000\nn-1\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(sign(SECRET, &canonical), "3b038d224b6b2e2e7691bf270e42c449977403a4cbdc97f46434c5a3be669d28");
+    }
+
+    #[test]
+    fn test_post_with_body_vector() {
+        let body = br#"{"to_address":"0x000000000000000000000000000000000000dEaD","amount":"0.1","network":"eth"}"#;
+        let canonical = canonical_request("POST", "/api/wallets/treasury/send", None, "1700000000", "n-2", body);
+        assert_eq!(
+            canonical,
+            "POST\n/api/wallets/treasury/send\n\n1700000000\nn-2\n\
+             3cb0b5b4659001761414c81c60d6fa0059f02abcd8662f82ade159c665ff3a21"
+        );
+        assert_eq!(sign(SECRET, &canonical), "8ffe2d2fc9b3862cccfdf2997c7a022cc3f22b089cb085de657d4d7f478075d3");
+        assert!(signature_matches(SECRET, &canonical, &sign(SECRET, &canonical)));
+        assert!(!signature_matches(SECRET, &canonical, &sign("wsk_other", &canonical)));
+        assert!(!signature_matches(SECRET, &canonical, "not-hex"));
+    }
+}
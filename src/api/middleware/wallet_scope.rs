@@ -0,0 +1,220 @@
+//! wallet范围 token 认证
+//!
+//! `wtk_` 前缀的 bearer token 是第三方集成用的wallet范围 token：只能访问
+//! 签发它的那个wallet，只能执行 capabilities 覆盖的操作，单笔发送不能超过
+//! `amount_cap`。其他 bearer token 仍按会话 token 处理。
+//!
+//! `auth_mode = "hmac"` 的 token 不作为 bearer 发送，而是对每个请求签名，
+//! 由 [`super::request_signing`] 中间件validate；validate通过后在这里得到同一个
+//! [`WalletTokenRecord`]，范围与金额上限的check完全相同。
+//!
+//! `dlg_` 前缀的是委托会话 token（见 `handlers::delegations`）：只能调用
+//! 其wallet的 send 端点，其他端点一律拒绝。
+
+use axum::http::HeaderMap;
+use std::sync::Arc;
+
+use super::extract_user::{extract_token, extract_user_id_from_token, verify_wallet_ownership};
+use super::request_signing::VERIFIED_KEY_HEADER;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::server::WalletServer;
+use crate::api::types::ApiError;
+use crate::api::validators::Amount;
+use crate::intents::DecisionContext;
+use crate::storage::{DelegationRecord, WalletCapability, WalletTokenRecord, AUTH_MODE_HMAC};
+
+/// wallet范围 token 的前缀
+pub const WALLET_TOKEN_PREFIX: &str = "wtk_";
+/// 委托会话 token 的前缀
+pub const DELEGATION_TOKEN_PREFIX: &str = "dlg_";
+
+type AuthError = ApiError;
+
+/// 请求方身份：会话user或wallet范围 token
+#[derive(Debug, Clone)]
+pub enum WalletCaller {
+    User(String),
+    Token(WalletTokenRecord),
+}
+
+impl WalletCaller {
+    /// 会话user本身，或 token 所属wallet的 owner
+    pub fn user_id(&self) -> &str {
+        match self {
+            WalletCaller::User(user_id) => user_id,
+            WalletCaller::Token(token) => &token.owner_user_id,
+        }
+    }
+
+    pub fn token_id(&self) -> Option<&str> {
+        match self {
+            WalletCaller::User(_) => None,
+            WalletCaller::Token(token) => Some(&token.id),
+        }
+    }
+
+    /// validate请求方可以对 `wallet_name` 执行 `capability`
+    ///
+    /// 会话user走所有权check；token 要求路径wallet就是签发它的wallet
+    /// （且该wallet关联仍是同一行，删除重建的同名wallet不继承 token）。
+    pub async fn authorize(
+        &self,
+        state: &Arc<WalletServer>,
+        wallet_name: &str,
+        capability: WalletCapability,
+    ) -> Result<(), AuthError> {
+        let token = match self {
+            WalletCaller::User(user_id) => {
+                return verify_wallet_ownership(user_id, wallet_name, state).await
+            }
+            WalletCaller::Token(token) => token,
+        };
+
+        let linked = if token.wallet_name == wallet_name {
+            state
+                .user_db
+                .find_user_wallet(&token.owner_user_id, wallet_name)
+                .await
+                .map_err(|e| {
+                    tracing::error!("wallet token scope lookup failed: {}", e);
+                    ApiError::new(ApiErrorCode::DbError, "Failed to query user wallets")
+                })?
+        } else {
+            None
+        };
+        if linked.map(|(id, _)| id) != Some(token.wallet_id) {
+            return Err(ApiError::new(
+                ApiErrorCode::WalletScopeMismatch,
+                "Forbidden: Token is not valid for this wallet",
+            ));
+        }
+        if !token.allows(capability) {
+            return Err(ApiError::new(
+                ApiErrorCode::TokenCapabilityDenied,
+                format!("Forbidden: Token lacks the {} capability", capability.name()),
+            ));
+        }
+        Ok(())
+    }
+
+    /// token 的单笔金额上限；会话user不受此限制
+    pub fn check_amount(&self, amount: &Amount) -> Result<(), AuthError> {
+        let Some(cap) = self.token_amount_cap() else {
+            return Ok(());
+        };
+        // 两者都是已validate的 Amount（≤18 位小数），parse_ether 精确
+        let exceeds = match (
+            ethers::utils::parse_ether(amount.as_str()),
+            ethers::utils::parse_ether(cap),
+        ) {
+            (Ok(amount), Ok(cap)) => amount > cap,
+            _ => true,
+        };
+        if exceeds {
+            return Err(ApiError::new(
+                ApiErrorCode::TokenAmountCapExceeded,
+                format!("Amount exceeds the token's per-transaction cap of {}", cap),
+            ));
+        }
+        Ok(())
+    }
+
+    fn token_amount_cap(&self) -> Option<&str> {
+        match self {
+            WalletCaller::User(_) => None,
+            WalletCaller::Token(token) => token.amount_cap.as_deref(),
+        }
+    }
+
+    /// 把 [`Self::check_amount`] 比较的 token 上限记入决策上下文；会话user不记录
+    pub fn record_token_limit(&self, decision: &mut DecisionContext) {
+        if let Some(token_id) = self.token_id() {
+            decision.record_token(token_id, self.token_amount_cap());
+        }
+    }
+
+    /// token 请求写一条审计记录（带 token id）；会话user不记录
+    pub async fn audit(&self, state: &Arc<WalletServer>, wallet_name: &str, action: &str) {
+        let Some(token_id) = self.token_id() else {
+            return;
+        };
+        let details = serde_json::json!({ "token_id": token_id, "action": action }).to_string();
+        if let Err(e) = state.storage.log_action(wallet_name, "wallet_token.use", &details, None, None).await {
+            tracing::error!("failed to audit wallet token {}: {}", token_id, e);
+        }
+    }
+}
+
+/// 签名validate通过的请求使用的 token id
+pub fn verified_key_id(headers: &HeaderMap) -> Option<&str> {
+    headers.get(VERIFIED_KEY_HEADER).and_then(|v| v.to_str().ok())
+}
+
+/// 签名请求的 token，或 Authorization 头中的wallet范围 token
+///
+/// 都不是时返回 `Ok(None)`；未知或已吊销返回 401 `INVALID_TOKEN`，
+/// 过期返回 401 `TOKEN_EXPIRED`。
+pub async fn extract_wallet_token(
+    headers: &HeaderMap,
+    state: &Arc<WalletServer>,
+) -> Result<Option<WalletTokenRecord>, AuthError> {
+    let lookup = if let Some(key_id) = verified_key_id(headers) {
+        // 签名请求：中间件之后 key 可能刚被吊销，重新按 id 加载
+        state.storage.find_wallet_token_by_id(key_id).await.map(|r| r.filter(|r| r.auth_mode == AUTH_MODE_HMAC))
+    } else {
+        let Some(token) = extract_token(headers).filter(|t| t.starts_with(WALLET_TOKEN_PREFIX)) else {
+            return Ok(None);
+        };
+        state.storage.find_wallet_token(token.trim()).await
+    };
+    let record = lookup.map_err(|e| {
+        tracing::error!("wallet token lookup failed: {}", e);
+        ApiError::new(ApiErrorCode::DbError, "Failed to validate token")
+    })?;
+    let record = record.ok_or_else(|| {
+        ApiError::new(ApiErrorCode::InvalidToken, "Unauthorized: Unknown or revoked token")
+    })?;
+    if record.is_expired(chrono::Utc::now().timestamp()) {
+        return Err(ApiError::new(ApiErrorCode::TokenExpired, "Unauthorized: Token has expired"));
+    }
+    Ok(Some(record))
+}
+
+/// Authorization 头中的委托会话 token 及其记录（明文 token 用于打开密封的sign密钥）
+///
+/// 不是委托 token 时返回 `Ok(None)`；未知或已吊销返回 401 `INVALID_TOKEN`。
+/// 过期不在这里拒绝，由发送路径返回 `DELEGATION_EXPIRED`。
+pub async fn extract_delegation(
+    headers: &HeaderMap,
+    state: &Arc<WalletServer>,
+) -> Result<Option<(DelegationRecord, String)>, AuthError> {
+    let Some(token) = extract_token(headers).filter(|t| t.starts_with(DELEGATION_TOKEN_PREFIX)) else {
+        return Ok(None);
+    };
+    let token = token.trim().to_string();
+    let record = state.storage.find_delegation(&token).await.map_err(|e| {
+        tracing::error!("delegation lookup failed: {}", e);
+        ApiError::new(ApiErrorCode::DbError, "Failed to validate token")
+    })?;
+    match record {
+        Some(record) => Ok(Some((record, token))),
+        None => Err(ApiError::new(ApiErrorCode::InvalidToken, "Unauthorized: Unknown or revoked token")),
+    }
+}
+
+/// wallet范围 token 优先，否则按会话 token 认证；委托 token 在这里一律拒绝
+pub async fn extract_wallet_caller(
+    headers: &HeaderMap,
+    state: &Arc<WalletServer>,
+) -> Result<WalletCaller, AuthError> {
+    if extract_token(headers).is_some_and(|t| t.starts_with(DELEGATION_TOKEN_PREFIX)) {
+        return Err(ApiError::new(
+            ApiErrorCode::DelegationScopeDenied,
+            "Forbidden: Delegation tokens may only send from their wallet",
+        ));
+    }
+    match extract_wallet_token(headers, state).await? {
+        Some(token) => Ok(WalletCaller::Token(token)),
+        None => extract_user_id_from_token(headers, state).await.map(WalletCaller::User),
+    }
+}
@@ -1,9 +1,14 @@
 // src/api/mod.rs
 
+pub mod admission;      // Concurrency caps for signing, unlock and export
+pub mod error_catalog;  // Every API error code with its status and retry semantics
 pub mod handlers;
+pub mod http_cache;     // ETags and the read-through response cache
 pub mod middleware;     // Authentication and other middleware
+pub mod pagination;     // Signed page cursors
 pub mod server;
 pub mod server_config;  // Server configuration constants
+pub mod streaming;      // Incremental JSON response bodies
 pub mod csp_middleware; // CSP security policy middleware
 pub mod types;
 pub mod validators;     // Shared validation logic
@@ -2,11 +2,11 @@
 
 use super::alchemy::AlchemyNFTClient;
 use super::types::*;
+use crate::api::error_catalog::ApiErrorCode;
 use crate::api::server::WalletServer;
-use crate::api::types::ErrorResponse;
+use crate::api::types::ApiError;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
     response::{IntoResponse, Response},
     Json,
 };
@@ -64,13 +64,7 @@ pub async fn get_nft_detail(
     // 解析 ID（格式：network:contract:tokenId）
     let parts: Vec<&str> = nft_id.split(':').collect();
     if parts.len() != 3 {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "无效的 NFT ID 格式，应为 network:contract:tokenId".to_string(),
-                code: "INVALID_NFT_ID".to_string(),
-            }),
-        )
+        return ApiError::new(ApiErrorCode::InvalidNftId, "无效的 NFT ID 格式，应为 network:contract:tokenId")
             .into_response();
     }
 
@@ -93,37 +87,19 @@ pub async fn transfer_nft(
 
     // validate输入
     if req.wallet_name.trim().is_empty() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Wallet name不能为空".to_string(),
-                code: "INVALID_INPUT".to_string(),
-            }),
-        )
+        return ApiError::new(ApiErrorCode::InvalidInput, "Wallet name不能为空")
             .into_response();
     }
 
     if req.to_address.trim().is_empty() || !req.to_address.starts_with("0x") {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "无效的Recipient address".to_string(),
-                code: "INVALID_ADDRESS".to_string(),
-            }),
-        )
+        return ApiError::new(ApiErrorCode::InvalidAddress, "无效的Recipient address")
             .into_response();
     }
 
     // checkwallet是否存在
     if let Err(e) = state.wallet_manager.get_wallet_by_name(&req.wallet_name).await {
         error!("wallet不存在: {:?}", e);
-        return (
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("wallet '{}' 不存在", req.wallet_name),
-                code: "WALLET_NOT_FOUND".to_string(),
-            }),
-        )
+        return ApiError::new(ApiErrorCode::WalletNotFound, format!("wallet '{}' 不存在", req.wallet_name))
             .into_response();
     }
 
@@ -3,7 +3,7 @@ use crate::api::server_config::*;
 use axum::{
     extract::Extension,
     http::StatusCode,
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use std::sync::Arc;
@@ -12,15 +12,64 @@ use tokio::net::TcpListener;
 use tower::{limit::ConcurrencyLimitLayer, timeout::TimeoutLayer, ServiceBuilder};
 use tower_http::{limit::RequestBodyLimitLayer, trace::TraceLayer, cors::CorsLayer};
 
+use crate::api::admission::{AdmissionController, Lane};
 use crate::api::handlers;
-use crate::core::config::WalletConfig;
+use crate::api::error_catalog;
+use crate::api::http_cache::{self, ResponseCache};
+use crate::blockchain::gas_oracle::{GasOracle, ProviderGasOracle};
+use crate::blockchain::recipient_guard::RecipientGuard;
+use crate::blockchain::bridge::BridgeFactory;
+use crate::blockchain::circuit_breaker::CircuitBreaker;
+use crate::blockchain::client_registry::ClientRegistry;
+use crate::blockchain::tx_status_hub::TxStatusHub;
+use crate::blockchain::tx_watch::TxWatchRegistry;
+use crate::core::config::{BridgeBackend, WalletConfig};
 use crate::core::errors::WalletError;
 use crate::core::wallet_manager::WalletManager;
+use crate::monitoring::{SecurityMonitor, WalletMetrics};
+use crate::ops::backfill::{HistoryBackfill, BACKFILL_JOB};
+use crate::ops::reconciliation::ReconciliationJob;
+use crate::ops::balance_snapshots::{BalanceSnapshotter, BALANCE_SNAPSHOTS_JOB};
+use crate::ops::block_tracking::{self, BlockTracker};
+use crate::ops::bridge_poller::{BridgePoller, BRIDGE_POLLER_JOB};
+use crate::ops::db_backup::{self, BackupScheduler};
+use crate::ops::deadman::{DeadmanEvaluator, DEADMAN_JOB};
+use crate::ops::jobs::JobRunner;
+use crate::ops::incidents::{self, ErrorTally};
+use crate::ops::fee_tracking::{ConfirmationPoller, GasPriceSampler, FEE_CONFIRMATIONS_JOB, GAS_SAMPLES_JOB};
+use crate::ops::leases::LeaderLease;
+use crate::ops::maintenance::MaintenanceMode;
+use crate::ops::tx_expiry::PendingExpiry;
+use crate::ops::pool_stats::PoolStatsSampler;
+use crate::ops::wal::{WalMaintenance, WAL_CHECKPOINT_JOB};
+use crate::crypto::multisig::MultiSignature;
+use crate::intents::{BroadcastChain, RpcBroadcastChain, SigningIntentLog};
+use crate::anomaly_detection::AnomalyDetector;
+use crate::approvals::ApprovalQueue;
+use crate::core::clock::{system_clock, Clock};
+use crate::core::ids::{random_ids, IdGenerator};
+use crate::erc4337::AccountAbstraction;
+use crate::pricing::{CachedPriceFeed, CoinGeckoFeed, PriceFeed};
+use crate::feature_flags::FeatureFlags;
+use crate::token_registry::TokenRegistry;
+use crate::timelocks::Timelocks;
+use crate::operations::BundleService;
+use crate::relay::{RelayService, WalletRelaySubmitter};
+use crate::security::key_usage::KeyUsageTracker;
+use crate::storage::{open_backend, QueryTimeouts, StorageBackend, WalSettings, WalletCacheSettings, WalletStorage};
 use crate::api::anomaly_detection;
 use crate::api::auth_simple;
+use crate::api::middleware::request_id;
+use crate::api::middleware::request_metrics;
+use crate::api::middleware::ApiKeyRing;
+use crate::api::middleware::request_signing::{self, NonceCache};
 use axum::error_handling::HandleErrorLayer;
 use tower::BoxError;
 
+/// Pending wallets younger than this are left alone on startup, so a restart
+/// never races a creation that another instance is still finishing.
+const PENDING_WALLET_GRACE_SECS: i64 = 60;
+
 #[derive(Clone)]
 pub struct WalletServer {
     pub wallet_manager: Arc<WalletManager>,
@@ -29,8 +78,40 @@ pub struct WalletServer {
     pub host: String,
     pub port: u16,
     pub config: WalletConfig,
-    pub api_key: Option<crate::security::SecretVec>,
+    pub api_key: ApiKeyRing, // admin API key(s); rotated through `/api/admin/rotate_api_key`
     pub rate_limiter: Arc<RateLimiter>, // SECURITY: Rate limiter to prevent DoS attacks
+    pub gas_oracle: Arc<dyn GasOracle>, // gas price / balance reads for pre-flight checks
+    pub recipient_guard: Arc<RecipientGuard>, // cached getCode checks before plain transfers
+    pub bridge_factory: Arc<BridgeFactory>, // bridge backend resolved once at startup
+    pub storage: Arc<WalletStorage>, // transaction records (admin triage reads this directly)
+    pub chain_clients: Arc<ClientRegistry>, // per-network clients for status rechecks
+    pub key_usage: Arc<KeyUsageTracker>, // signing key usage accounting / rotation policy
+    pub maintenance: Arc<MaintenanceMode>, // background jobs pause while enabled
+    pub circuit_breaker: Arc<CircuitBreaker>, // per-network RPC failure tracking
+    pub backups: Arc<BackupScheduler>, // scheduled and on-demand database backups
+    pub request_nonces: Arc<NonceCache>, // replay protection for HMAC-signed requests
+    pub wal: Arc<WalMaintenance>, // WAL checkpoints and database size gauges
+    pub relay: Arc<RelayService>, // EIP-2771 meta-transaction relay
+    pub account_abstraction: Arc<AccountAbstraction>, // ERC-4337 UserOperation sending and tracking
+    pub multisig: Arc<tokio::sync::Mutex<MultiSignature>>, // open tiered multisig proposals
+    pub signing_intents: Arc<SigningIntentLog>, // write-ahead log for server-side sends
+    pub approvals: Arc<ApprovalQueue>, // sends held for four-eyes approval
+    pub bundles: Arc<BundleService>, // multi-step operation bundles
+    pub send_detector: Arc<tokio::sync::Mutex<AnomalyDetector>>, // screens server-signed sends before signing
+    pub price_feed: Option<Arc<dyn PriceFeed>>, // fiat prices; None when pricing is disabled
+    pub tx_watches: Arc<TxWatchRegistry>, // shared pollers behind /api/transactions/:id/wait
+    pub tx_status: Arc<TxStatusHub>, // status pushes to /api/ws subscribers
+    pub jobs: Arc<JobRunner>, // supervised background jobs, started by `start`
+    pub response_cache: Arc<ResponseCache>, // ETag-versioned bodies of the routes in `http_cache::CACHEABLE_ROUTES`
+    pub admission: Arc<AdmissionController>, // concurrency caps for signing, unlock and export routes
+    pub errors: Arc<ErrorTally>, // failed requests by error class, for `/api/admin/incidents`
+    pub metrics: Arc<WalletMetrics>, // Prometheus metrics of the server, its middleware and background jobs
+    pub backfill: Arc<HistoryBackfill>, // on-chain history import for wallets added with existing funds
+    pub flags: Arc<FeatureFlags>, // runtime feature flags, managed through `/api/admin/flags`
+    pub reconciliation: Arc<ReconciliationJob>, // runs queued through `/api/admin/reconciliations`
+    pub tokens: Arc<TokenRegistry>, // non-standard ERC-20 behaviors, managed through `/api/admin/tokens`
+    pub timelocks: Arc<Timelocks>, // sealed time-locked transactions and their nonce lanes
+    pub sandbox: bool, // database carries `sandbox=true`; mainnet networks were dropped from `config`
 }
 
 impl WalletServer {
@@ -40,7 +121,61 @@ impl WalletServer {
         config: WalletConfig,
         api_key: Option<crate::security::SecretVec>,
     ) -> Result<Self, WalletError> {
-        let wallet_manager = Arc::new(WalletManager::new(&config).await?);
+        Self::new_with_mock_policy(host, port, config, api_key, false).await
+    }
+
+    /// Like [`WalletServer::new`], but lets the caller opt in to mock backends
+    /// (`--allow-insecure-mocks`). Without the opt-in, `bridge_backend = mock`
+    /// is rejected unless the binary was built with `test-env`.
+    pub async fn new_with_mock_policy(
+        host: String,
+        port: u16,
+        config: WalletConfig,
+        api_key: Option<crate::security::SecretVec>,
+        allow_insecure_mocks: bool,
+    ) -> Result<Self, WalletError> {
+        Self::new_with_sources(host, port, config, api_key, allow_insecure_mocks, system_clock(), random_ids()).await
+    }
+
+    /// Like [`WalletServer::new_with_mock_policy`], with the clock and id
+    /// generator that storage and the wallet manager stamp new records with.
+    pub async fn new_with_sources(
+        host: String,
+        port: u16,
+        mut config: WalletConfig,
+        api_key: Option<crate::security::SecretVec>,
+        allow_insecure_mocks: bool,
+        clock: Arc<dyn Clock>,
+        ids: Arc<dyn IdGenerator>,
+    ) -> Result<Self, WalletError> {
+        config.blockchain.validate()?;
+        let bridge_backend = config.bridge_backend.resolve(allow_insecure_mocks)?;
+        config.bridge_backend = bridge_backend;
+        if bridge_backend == BridgeBackend::Mock {
+            tracing::warn!("⚠️ Bridge backend: mock (simulated transfers, not for production)");
+        } else {
+            tracing::info!("Bridge backend: {}", bridge_backend.as_str());
+        }
+        let mut bridge_factory = BridgeFactory::new(bridge_backend);
+        if bridge_backend == BridgeBackend::Real && crate::api::bridge_lifi::is_lifi_enabled() {
+            // 仅用于路由发现；LI.FI 执行尚未接入
+            bridge_factory =
+                bridge_factory.with_bridge(Arc::new(crate::blockchain::bridge::LiFiBridge::new()));
+        }
+        let bridge_factory = Arc::new(bridge_factory);
+
+        // sandbox 数据库里的wallet是克隆出来的，不能连到主网
+        let sandbox = crate::storage::probe_sandbox(&config.storage.database_url)
+            .await
+            .map_err(|e| WalletError::StorageError(format!("sandbox 标记读取failed: {}", e)))?;
+        if sandbox {
+            let dropped = config.blockchain.remove_mainnets();
+            tracing::warn!("⚠️ SANDBOX DATABASE: mainnet networks are disabled (dropped: {:?})", dropped);
+        }
+
+        let wallet_manager = Arc::new(
+            WalletManager::new(&config).await?.with_clock(clock.clone()).with_id_generator(ids.clone()),
+        );
         
         // ✅ 初始化user数据库
         let users_db_url = std::env::var("USERS_DATABASE_URL")
@@ -54,7 +189,256 @@ impl WalletServer {
         // SECURITY: Initialize rate limiter to prevent DoS attacks
         // Allow 100 requests per minute per IP
         let rate_limiter = Arc::new(RateLimiter::new(100, Duration::from_secs(60)));
-        Ok(Self { wallet_manager, user_db, session_store, host, port, config, api_key, rate_limiter })
+
+        let metrics = Arc::new(
+            WalletMetrics::new()
+                .map_err(|e| WalletError::InternalError(format!("metrics初始化failed: {}", e)))?,
+        );
+
+        let backend = open_backend(&config.storage.database_url, WalSettings::from(&config.wal))
+            .await
+            .map_err(|e| WalletError::StorageError(format!("storage初始化failed: {}", e)))?;
+        // journal, approvals, nonce lanes and the other server tables only exist in SQLite
+        let mut storage = match backend {
+            StorageBackend::Sqlite(storage) => storage,
+            #[cfg(feature = "postgres")]
+            StorageBackend::Postgres(_) => {
+                return Err(WalletError::ConfigError(
+                    "the API server needs a sqlite: DATABASE_URL; PostgreSQL only backs WalletStorageTrait".into(),
+                ))
+            }
+        }
+        .with_metrics(metrics.clone())
+            .with_query_timeouts(QueryTimeouts::from(&config.database_queries))
+            .with_clock(clock.clone())
+            .with_id_generator(ids);
+        if let Some(read_url) = &config.storage.read_database_url {
+            storage = storage
+                .with_read_replica(read_url)
+                .await
+                .map_err(|e| WalletError::StorageError(format!("read replica初始化failed: {}", e)))?;
+        }
+        if config.cluster.multi_instance {
+            storage = storage.with_nonce_leases(Duration::from_millis(config.cluster.nonce_lease_ms));
+        }
+        storage = storage.with_wallet_cache(WalletCacheSettings {
+            ttl: Duration::from_secs(config.cluster.wallet_cache_ttl_secs),
+            epoch_check_interval: Duration::from_millis(config.cluster.cache_epoch_check_ms),
+        });
+        tracing::info!("Instance id: {}", storage.instance_id());
+        let storage = Arc::new(storage);
+
+        let gas_oracle: Arc<dyn GasOracle> =
+            Arc::new(ProviderGasOracle::from_config(&config.blockchain));
+        let recipient_guard = Arc::new(RecipientGuard::from_config(&config.recipient_guard));
+        let chain_clients = Arc::new(ClientRegistry::from_config_with_metrics(&config.blockchain, metrics.clone()));
+        let security_monitor =
+            Arc::new(SecurityMonitor::new(metrics.clone()).with_journal(storage.clone()));
+        let key_usage = Arc::new(KeyUsageTracker::new(
+            storage.clone(),
+            config.security.key_rotation.clone(),
+            metrics.clone(),
+            security_monitor,
+        ));
+        let maintenance = Arc::new(MaintenanceMode::new());
+        let backup_sink = db_backup::sink::from_config(&config.backups.sink)
+            .map_err(|e| WalletError::ConfigError(format!("backup sink: {}", e)))?;
+        let backups = BackupScheduler::new(
+            config.backups.clone(),
+            storage.clone(),
+            Arc::from(backup_sink),
+            maintenance.clone(),
+            metrics.clone(),
+        );
+        let backups_lease = LeaderLease::for_scheduler(
+            storage.clone(),
+            db_backup::BACKUPS_JOB,
+            backups.interval(),
+            Duration::from_secs(config.cluster.lease_grace_secs),
+        );
+        let backups = Arc::new(backups.with_lease(backups_lease));
+        let request_nonces = Arc::new(NonceCache::new(storage.clone()));
+        let wal = WalMaintenance::new(config.wal.clone(), storage.clone(), metrics.clone());
+        let wal_lease = LeaderLease::for_scheduler(
+            storage.clone(),
+            WAL_CHECKPOINT_JOB,
+            wal.interval(),
+            Duration::from_secs(config.cluster.lease_grace_secs),
+        );
+        let wal = Arc::new(wal.with_lease(wal_lease));
+        let backfill =
+            HistoryBackfill::from_config(&config.backfill, storage.clone(), chain_clients.clone(), metrics.clone());
+        let backfill_lease = LeaderLease::for_scheduler(
+            storage.clone(),
+            BACKFILL_JOB,
+            backfill.interval(),
+            Duration::from_secs(config.cluster.lease_grace_secs),
+        );
+        let backfill = Arc::new(backfill.with_lease(backfill_lease));
+        let reconciliation = Arc::new(ReconciliationJob::new(
+            wallet_manager.clone(),
+            storage.clone(),
+            backfill.clone(),
+            chain_clients.clone(),
+        ));
+        let flags = Arc::new(
+            FeatureFlags::from_config(&config, storage.clone()).map_err(|e| WalletError::ConfigError(e.to_string()))?,
+        );
+        let tokens = Arc::new(
+            TokenRegistry::from_config(&config, storage.clone()).map_err(|e| WalletError::ConfigError(e.to_string()))?,
+        );
+        let relay = Arc::new(
+            RelayService::new(
+                config.relay.clone(),
+                storage.clone(),
+                Arc::new(WalletRelaySubmitter::new(wallet_manager.clone(), &config.relay)),
+            )?
+            .with_flags(flags.clone()),
+        );
+        let account_abstraction = Arc::new(AccountAbstraction::new(
+            config.account_abstraction.clone(),
+            &config.blockchain,
+            storage.clone(),
+        )?);
+        let admission = Arc::new(AdmissionController::new(config.admission.clone()).with_metrics(metrics.clone()));
+        let signing_intents = Arc::new(
+            SigningIntentLog::new(storage.clone(), Arc::new(RpcBroadcastChain::new(wallet_manager.clone())))
+                .with_duplicate_window(config.security.duplicate_send_window_secs)
+                .with_tx_encoding(config.security.tx_encoding)
+                .with_metrics(metrics.clone()),
+        );
+        let approvals = Arc::new(ApprovalQueue::new(config.approvals.clone(), storage.clone()));
+        let timelocks = Arc::new(
+            Timelocks::new(config.timelocks.clone(), &config.blockchain, storage.clone())
+                .with_tx_encoding(config.security.tx_encoding),
+        );
+        let bundles = Arc::new(BundleService::new(storage.clone()).with_flags(flags.clone()));
+        let send_detector =
+            Arc::new(tokio::sync::Mutex::new(AnomalyDetector::with_config(config.anomaly_detection.clone())));
+        let price_feed = config.pricing.enabled.then(|| {
+            let upstream = Arc::new(CoinGeckoFeed::from_config(&config.pricing));
+            Arc::new(CachedPriceFeed::from_config(upstream, &config.pricing)) as Arc<dyn PriceFeed>
+        });
+        let jobs = Arc::new(JobRunner::new(config.jobs.clone(), maintenance.clone()).with_clock(clock));
+        let multi_sig_threshold = config.multi_sig_threshold;
+        Ok(Self {
+            wallet_manager,
+            user_db,
+            session_store,
+            host,
+            port,
+            config,
+            api_key: ApiKeyRing::from(api_key),
+            rate_limiter,
+            gas_oracle,
+            recipient_guard,
+            bridge_factory,
+            storage,
+            chain_clients,
+            key_usage,
+            maintenance,
+            circuit_breaker: Arc::new(CircuitBreaker::default()),
+            backups,
+            request_nonces,
+            wal,
+            relay,
+            account_abstraction,
+            multisig: Arc::new(tokio::sync::Mutex::new(MultiSignature::new(multi_sig_threshold))),
+            signing_intents,
+            approvals,
+            bundles,
+            send_detector,
+            price_feed,
+            tx_watches: Arc::new(TxWatchRegistry::default()),
+            tx_status: Arc::new(TxStatusHub::default()),
+            jobs,
+            response_cache: Arc::new(ResponseCache::default()),
+            admission,
+            errors: Arc::new(ErrorTally::new()),
+            metrics,
+            backfill,
+            flags,
+            reconciliation,
+            tokens,
+            timelocks,
+            sandbox,
+        })
+    }
+
+    /// Replace the gas oracle (tests inject a MockProvider-backed oracle here).
+    pub fn with_gas_oracle(mut self, gas_oracle: Arc<dyn GasOracle>) -> Self {
+        self.gas_oracle = gas_oracle;
+        self
+    }
+
+    /// Replace the history backfill (tests read history from a scripted chain).
+    pub fn with_backfill(mut self, backfill: HistoryBackfill) -> Self {
+        self.backfill = Arc::new(backfill);
+        self
+    }
+
+    /// Replace the reconciliation job (tests read receipts from a scripted chain).
+    pub fn with_reconciliation(mut self, reconciliation: ReconciliationJob) -> Self {
+        self.reconciliation = Arc::new(reconciliation);
+        self
+    }
+
+    /// Replace the price feed (tests inject a feed backed by a mock HTTP server).
+    pub fn with_price_feed(mut self, price_feed: Arc<dyn PriceFeed>) -> Self {
+        self.price_feed = Some(price_feed);
+        self
+    }
+
+    /// Replace the per-network client registry (tests register MockProvider clients).
+    pub fn with_chain_clients(mut self, chain_clients: ClientRegistry) -> Self {
+        self.chain_clients = Arc::new(chain_clients);
+        self
+    }
+
+    /// Replace the confirmation watch registry (tests shorten the poll interval).
+    pub fn with_tx_watches(mut self, tx_watches: TxWatchRegistry) -> Self {
+        self.tx_watches = Arc::new(tx_watches);
+        self
+    }
+
+    /// Replace the WebSocket status hub (tests shorten the poll interval and backoff).
+    pub fn with_tx_status_hub(mut self, tx_status: TxStatusHub) -> Self {
+        self.tx_status = Arc::new(tx_status);
+        self
+    }
+
+    /// Accept `secondary` alongside the primary API key (rotation overlap).
+    /// Ignored when no primary key is configured.
+    pub fn with_secondary_api_key(self, secondary: crate::security::SecretVec) -> Self {
+        if !self.api_key.set_secondary(secondary) {
+            tracing::warn!("secondary API key ignored: no primary API key is configured");
+        }
+        self
+    }
+
+    /// Replace the RPC used by the signing critical section (tests simulate the node).
+    pub fn with_broadcast_chain(mut self, chain: Arc<dyn BroadcastChain>) -> Self {
+        self.signing_intents = Arc::new(
+            SigningIntentLog::new(self.storage.clone(), chain)
+                .with_duplicate_window(self.config.security.duplicate_send_window_secs)
+                .with_tx_encoding(self.config.security.tx_encoding)
+                .with_metrics(self.metrics.clone()),
+        );
+        self
+    }
+
+    /// Replace the anomaly detector that screens bundle counterparties.
+    pub fn with_bundle_detector(mut self, detector: AnomalyDetector) -> Self {
+        self.bundles =
+            Arc::new(BundleService::new(self.storage.clone()).with_detector(detector).with_flags(self.flags.clone()));
+        self
+    }
+
+    /// Replace the anomaly detector that screens server-signed sends (tests
+    /// register extra rule plugins on it first).
+    pub fn with_send_detector(mut self, detector: AnomalyDetector) -> Self {
+        self.send_detector = Arc::new(tokio::sync::Mutex::new(detector));
+        self
     }
 
     /// Test-only constructor used by integration tests.
@@ -65,6 +449,21 @@ impl WalletServer {
         config: WalletConfig,
         api_key: Option<crate::security::SecretVec>,
         test_master_key: Option<crate::security::SecretVec>,
+    ) -> Result<Self, WalletError> {
+        Self::new_for_test_with_sources(bind_addr, port, config, api_key, test_master_key, system_clock(), random_ids())
+            .await
+    }
+
+    /// [`WalletServer::new_for_test`] with an injected clock and id generator
+    /// (deterministic fixtures, e.g. `FixedClock` and `SequentialIds`).
+    pub async fn new_for_test_with_sources(
+        bind_addr: String,
+        port: u16,
+        mut config: WalletConfig,
+        api_key: Option<crate::security::SecretVec>,
+        test_master_key: Option<crate::security::SecretVec>,
+        clock: Arc<dyn Clock>,
+        ids: Arc<dyn IdGenerator>,
     ) -> Result<Self, WalletError> {
         // Ensure integration tests (which compile the library without the
         // `test-env` feature) still get the deterministic test env guards when
@@ -90,8 +489,11 @@ impl WalletServer {
         
         #[cfg(not(any(test, feature = "test-env")))]
         let _ = test_master_key; // silence unused warning
+        // The test constructor is itself the explicit opt-in for mock bridges.
+        config.bridge_backend = BridgeBackend::Mock;
         // delegate to primary constructor which will create WalletManager etc.
-        let mut server = WalletServer::new(bind_addr, port, config, api_key).await?;
+        let mut server =
+            WalletServer::new_with_sources(bind_addr, port, config, api_key, true, clock, ids).await?;
         // Override rate limiter for tests to allow unlimited requests
         server.rate_limiter = Arc::new(RateLimiter::new(10000, Duration::from_secs(1)));
         Ok(server)
@@ -117,28 +519,162 @@ impl WalletServer {
         
         let auth_router = auth_simple::create_auth_routes(auth_state);
 
+        // 昂贵操作（sign / 解锁 / 导出）经准入控制排队；等待计入各自路由的超时
+        let interactive = state.admission.route(Lane::Interactive, REQUEST_TIMEOUT);
+        let sensitive_interactive = state.admission.route(Lane::Interactive, SENSITIVE_REQUEST_TIMEOUT);
+        let sensitive_batch = state.admission.route(Lane::Batch, SENSITIVE_REQUEST_TIMEOUT);
+
         let base_router = Router::new()
             .route("/health", get(handlers::health_check))  // ✅ 添加根路径健康check
             .route("/api/health", get(handlers::health_check))
             .route("/api/system/info", get(crate::api::handlers::system_info::system_info))
+            .route("/api/version", get(handlers::version))
             .route("/api/wallets", post(handlers::create_wallet).get(handlers::list_wallets))
-            .route("/api/wallets/:name", delete(handlers::delete_wallet))
+            .route("/api/wallets/:name", delete(handlers::delete_wallet).patch(handlers::update_wallet_notes))
             .route("/api/wallets/:name/address", get(handlers::get_wallet_address))
+            .route("/api/wallets/:name/networks", put(handlers::put_allowed_networks).get(handlers::get_allowed_networks))
+            .route("/api/wallets/:name/networks/:network/initialize", post(handlers::initialize_wallet_network))
             .route("/api/wallets/:name/addresses", post(handlers::get_wallet_address).get(handlers::get_wallet_address))  // ✅ 添加addresses路由（复数形式）
+            .route("/api/wallets/:name/payment_uri", get(handlers::wallet_payment_uri))
             .route("/api/wallets/:name/balance", get(handlers::get_balance))
+            .route("/api/wallets/:name/allowance", get(handlers::get_allowance))
+            .route("/api/wallets/:name/balance_history", get(handlers::balance_history))
+            .route("/api/wallets/:name/funding_requirements", get(handlers::funding_requirements))
             .route("/api/wallets/:name/assets", get(crate::api::handlers::multi_assets::get_multi_assets))
             .route("/api/wallets/:name/multi-assets", get(crate::api::handlers::multi_assets::get_multi_assets))  // ✅ 别名路由
             .route("/api/wallets/:name/history", get(handlers::get_transaction_history))
+            .route("/api/wallets/:name/backfill", post(handlers::request_backfill))
+            .route("/api/wallets/:name/backfill/status", get(handlers::backfill_status))
             .route("/api/wallets/:name/transactions", get(handlers::get_transaction_history)) // 别名
-            .route("/api/wallets/:name/backup", get(handlers::backup_wallet))
+            .route("/api/wallets/:name/backup", interactive.clone().wrap(get(handlers::backup_wallet)))
+            .route("/api/wallets/:name/backup/shamir", interactive.clone().wrap(post(handlers::backup_wallet_shamir)))
             .route("/api/wallets/restore", post(handlers::restore_wallet))
+            .route("/api/wallets/restore/shamir", post(handlers::restore_wallet_shamir))
+            .route("/api/wallets/import", post(handlers::import_wallet))
+            .route("/api/wallets/:name/export", interactive.clone().wrap(post(handlers::export_wallet)))
+            .route("/api/wallets/import_keystore", post(handlers::import_keystore))
+            .route("/api/wallets/:name/export_keystore", interactive.clone().wrap(post(handlers::export_keystore)))
+            .route("/api/wallets/import_enveloped_keystore", post(handlers::import_enveloped_keystore))
+            .route(
+                "/api/wallets/:name/export_enveloped_keystore",
+                interactive.clone().wrap(post(handlers::export_enveloped_keystore)),
+            )
             .route("/api/wallets/:name/rotate-signing-key", post(handlers::rotate_signing_key))
-            .route("/api/wallets/:name/send_multi_sig", post(handlers::send_multi_sig_transaction))
+            .route("/api/wallets/:name/key_usage", get(handlers::key_usage))
+            .route(
+                "/api/wallets/:name/deadman",
+                post(handlers::put_deadman_policy).get(handlers::get_deadman_policy).delete(handlers::cancel_deadman_policy),
+            )
+            .route("/api/wallets/:name/deadman/checkin", post(handlers::deadman_checkin))
+            .route("/api/wallets/:name/attestations", post(handlers::create_attestation).get(handlers::list_attestations))
+            .route("/api/wallets/:name/attestations/:id/revoke", post(handlers::revoke_attestation))
+            .route("/api/wallets/:name/address_book/import", post(handlers::import_address_book))
+            .route("/api/wallets/:name/address_book/export", get(handlers::export_address_book))
+            .route(
+                "/api/wallets/:name/tokens",
+                post(handlers::create_wallet_token).get(handlers::list_wallet_tokens),
+            )
+            .route("/api/wallets/:name/tokens/:token_id", delete(handlers::revoke_wallet_token))
+            .route(
+                "/api/wallets/:name/delegations",
+                post(handlers::create_delegation).get(handlers::list_delegations),
+            )
+            .route(
+                "/api/wallets/:name/delegations/:id",
+                get(handlers::get_delegation).delete(handlers::revoke_delegation),
+            )
+            .route(
+                "/api/wallets/:name/timelocks",
+                post(handlers::create_timelock).get(handlers::list_timelocks),
+            )
+            .route(
+                "/api/wallets/:name/subscriptions",
+                post(handlers::create_balance_subscription).get(handlers::list_balance_subscriptions),
+            )
+            .route(
+                "/api/wallets/:name/subscriptions/:id",
+                patch(handlers::update_balance_subscription).delete(handlers::delete_balance_subscription),
+            )
+            .route(
+                "/api/wallets/:name/send_multi_sig",
+                interactive.clone().wrap(post(handlers::send_multi_sig_transaction)),
+            )
+            .route(
+                "/api/wallets/:name/multisig/policy",
+                put(handlers::put_multisig_policy).get(handlers::get_multisig_policy),
+            )
+            .route("/api/wallets/:name/multisig/proposals", post(handlers::create_multisig_proposal))
+            .route("/api/wallets/:name/multisig/proposals/:id", get(handlers::get_multisig_proposal))
+            .route("/api/wallets/:name/aa/operations/:op_hash", get(handlers::aa_operation))
+            .route(
+                "/api/wallets/:name/review_threshold",
+                put(handlers::put_review_threshold).get(handlers::get_review_threshold),
+            )
+            .route("/api/wallets/:name/limits", put(handlers::put_spending_limits).get(handlers::get_spending_limits))
+            .route("/api/wallets/:name/nonces/reconcile", post(handlers::reconcile_nonces))
+            // Named wallet groups
+            .route("/api/groups", post(handlers::create_group).get(handlers::list_groups))
+            .route("/api/groups/:name", get(handlers::get_group).delete(handlers::delete_group))
+            .route(
+                "/api/groups/:name/members/:wallet",
+                put(handlers::add_group_member).delete(handlers::remove_group_member),
+            )
+            .route("/api/groups/:name/balances", get(handlers::group_balances))
+            .route("/api/groups/:name/history", get(handlers::group_history))
             // Independent transactions API
             .route("/api/transactions/history", get(handlers::transactions_history))
-            .route("/api/transactions/send", post(handlers::transactions_send))
+            .route("/api/transactions/send", interactive.global_only().wrap(post(handlers::transactions_send)))
             .route("/api/transactions/:id/status", get(handlers::transaction_status))
+            .route("/api/transactions/:id/wait", get(handlers::wait_for_transaction))
+            .route("/api/ws", get(handlers::transaction_status_ws))
+            .route("/api/networks", get(handlers::list_networks))
+            .route("/api/errors", get(handlers::list_error_codes))
+            .route("/api/networks/:network/transactions/:hash", get(handlers::inspect_transaction))
+            .route("/api/validate/address", get(handlers::validate_address))
+            .route("/api/parse/payment_uri", post(handlers::decode_payment_uri))
+            .route("/api/attestations/verify", post(handlers::verify_attestation))
+            .route("/api/reports/verify", post(handlers::verify_reserve_report))
+            .route("/api/bundles/:id", get(handlers::get_bundle))
             .route("/api/metrics", get(handlers::metrics))
+            // Operations triage (API key)
+            .route("/api/admin/summary", get(handlers::admin_summary))
+            .route("/api/admin/rotate_api_key", post(handlers::rotate_api_key))
+            .route("/api/admin/jobs", get(handlers::list_jobs))
+            .route("/api/admin/jobs/:name/run", post(handlers::run_job))
+            .route("/api/admin/incidents", get(handlers::list_incidents))
+            .route("/api/admin/flags", get(handlers::list_flags).put(handlers::put_flag))
+            .route("/api/admin/tokens", get(handlers::list_tokens))
+            .route(
+                "/api/admin/tokens/:network/:address",
+                put(handlers::put_token_behavior).delete(handlers::clear_token_behavior),
+            )
+            .route("/api/admin/reconciliations", post(handlers::create_reconciliation))
+            .route("/api/admin/reconciliations/:id", get(handlers::get_reconciliation))
+            .route("/api/admin/transactions", get(handlers::admin_transactions))
+            .route("/api/admin/transactions/recheck", post(handlers::recheck_transactions))
+            .route("/api/admin/transactions/broadcast", post(handlers::broadcast_raw_transaction))
+            .route("/api/admin/audit-logs", get(handlers::list_audit_logs))
+            .route("/api/admin/backups", get(handlers::list_backups))
+            .route("/api/admin/backups/run", post(handlers::run_backup))
+            .route("/api/admin/db/checkpoint", post(handlers::db_checkpoint))
+            .route("/api/admin/db/vacuum-into", post(handlers::db_vacuum_into))
+            .route("/api/admin/intents/reconcile", post(handlers::reconcile_intents))
+            .route("/api/admin/wallet-profiles/rebuild", post(handlers::rebuild_wallet_profiles))
+            // Custodial wallets, driven by `hot_wallet client`
+            .route("/api/admin/wallets", get(handlers::list_custodial_wallets).post(handlers::create_custodial_wallet))
+            .route("/api/admin/wallets/:name/balance", post(handlers::custodial_wallet_balance))
+            .route("/api/admin/users/:id/erase", post(handlers::erase_user))
+            .route("/api/admin/users/:id/erasure-certificates", get(handlers::list_erasure_certificates))
+            .route(
+                "/api/admin/reports/proof_of_reserves",
+                post(handlers::create_reserve_report).get(handlers::list_reserve_reports),
+            )
+            .route("/api/admin/reports/proof_of_reserves/:id", get(handlers::get_reserve_report))
+            // Four-eyes approval of held sends
+            .route("/api/approvals", get(handlers::list_approvals))
+            .route("/api/approvals/:id/reject", post(handlers::reject_approval))
+            // Ordered change feed for downstream consumers (API key)
+            .route("/api/events", get(handlers::list_events))
             .layer(
                 CorsLayer::new()
                     .allow_origin({
@@ -174,6 +710,11 @@ impl WalletServer {
                         axum::http::header::ACCESS_CONTROL_REQUEST_METHOD,
                         axum::http::header::ACCESS_CONTROL_REQUEST_HEADERS,
                         axum::http::HeaderName::from_static("x-api-key"),
+                        axum::http::HeaderName::from_static(request_signing::HEADER_KEY_ID),
+                        axum::http::HeaderName::from_static(request_signing::HEADER_TIMESTAMP),
+                        axum::http::HeaderName::from_static(request_signing::HEADER_NONCE),
+                        axum::http::HeaderName::from_static(request_signing::HEADER_SIGNATURE),
+                        axum::http::HeaderName::from_static("x-wallet-password"),
                     ])
                     .expose_headers([
                         axum::http::header::CONTENT_TYPE,
@@ -203,10 +744,27 @@ impl WalletServer {
 
         // Sensitive endpoints sub-router with stricter limits and per-route timeout
         let sensitive = Router::new()
-            .route("/api/wallets/:name/send", post(handlers::send_transaction))
+            .route("/api/wallets/:name/send", sensitive_interactive.clone().wrap(post(handlers::send_transaction)))
+            .route("/api/wallets/:name/send_batch", sensitive_batch.clone().wrap(post(handlers::send_batch)))
+            .route("/api/wallets/:name/approve", sensitive_interactive.clone().wrap(post(handlers::approve_token)))
+            .route("/api/wallets/:name/aa/send", sensitive_interactive.clone().wrap(post(handlers::aa_send)))
+            .route("/api/wallets/:name/bundles", sensitive_batch.clone().wrap(post(handlers::submit_bundle)))
+            .route("/api/bundles/:id/resume", sensitive_batch.clone().global_only().wrap(post(handlers::resume_bundle)))
+            // `:name` 是分组名而不是wallet
+            .route("/api/groups/:name/sweep", sensitive_batch.global_only().wrap(post(handlers::sweep_group)))
+            .route(
+                "/api/approvals/:id/approve",
+                sensitive_interactive.clone().global_only().wrap(post(handlers::approve_approval)),
+            )
+            .route(
+                "/api/timelocks/:id/release",
+                sensitive_interactive.clone().global_only().wrap(post(handlers::release_timelock)),
+            )
             .route("/api/bridge", post(handlers::bridge::bridge_assets))
             .route("/api/bridge/history", get(handlers::bridge_history))
+            .route("/api/bridge/routes", get(handlers::bridge_routes))
             .route("/api/bridge/:id/status", get(handlers::bridge_status))
+            .route("/api/analytics/fees", get(handlers::fee_analytics))
             // DEX 交换路由
             .route("/api/swap/quote", get(crate::api::swap::swap_quote))
             .route("/api/swap/execute", post(crate::api::swap::swap_execute))
@@ -218,6 +776,21 @@ impl WalletServer {
             .route("/api/gamefi/assets/:wallet", get(crate::api::gamefi::get_game_assets))
             .route("/api/airdrops/:wallet", get(crate::api::gamefi::get_airdrops))
             .route("/api/airdrops/:id/claim", post(crate::api::gamefi::claim_airdrop))
+            // EIP-2771 元transaction中继
+            .route("/api/relay/meta_tx", post(handlers::relay_meta_tx).get(handlers::list_meta_tx_relays));
+        // Bitcoin watch-only 导出（xpub / descriptor，需要解密主密钥）
+        #[cfg(feature = "bitcoin")]
+        let sensitive = sensitive.route(
+            "/api/wallets/:name/btc/descriptor",
+            sensitive_interactive.wrap(get(handlers::export_btc_descriptor)),
+        );
+        // sandbox 克隆只在非生产构建中注册
+        #[cfg(any(test, feature = "test-env"))]
+        let sensitive = sensitive.route("/api/admin/sandbox-clone", post(handlers::sandbox_clone));
+        // 错误码注入，供错误目录一致性测试使用
+        #[cfg(any(test, feature = "test-env"))]
+        let sensitive = sensitive.route("/api/test/errors/:code", get(handlers::inject_error));
+        let sensitive = sensitive
             .layer(
                 ServiceBuilder::new()
                     .layer(HandleErrorLayer::new(|err: BoxError| async move {
@@ -241,10 +814,15 @@ impl WalletServer {
         let app = base_router
             .merge(sensitive)
             .merge(preferences_router)  // ✅ 在with_state()之前merge
+            // HMAC 签名请求在进入 handler 前validate
+            .layer(axum::middleware::from_fn_with_state(state.clone(), request_signing::verify_signed_requests))
+            // 最外层：签名校验拒绝的请求也计入指标
+            .layer(axum::middleware::from_fn_with_state(state.clone(), request_metrics::record_request_metrics))
             .with_state(state.clone());
 
-        // Anomaly detection sub-router with its own state
-        let anomaly_state = anomaly_detection::AnomalyApiState::new();
+        // Anomaly detection sub-router with its own state; it shares the detector
+        // that screens server-signed sends, so rules and stats seen there apply here too
+        let anomaly_state = anomaly_detection::AnomalyApiState::with_detector(state.send_detector.clone());
         let anomaly_router = Router::new()
             .nest("/api/anomaly-detection", anomaly_detection::create_anomaly_routes(anomaly_state));
 
@@ -284,96 +862,264 @@ impl WalletServer {
                 axum::http::header::ACCESS_CONTROL_REQUEST_METHOD,
                 axum::http::header::ACCESS_CONTROL_REQUEST_HEADERS,
                 axum::http::HeaderName::from_static("x-api-key"),
+                axum::http::HeaderName::from_static(request_signing::HEADER_KEY_ID),
+                axum::http::HeaderName::from_static(request_signing::HEADER_TIMESTAMP),
+                axum::http::HeaderName::from_static(request_signing::HEADER_NONCE),
+                axum::http::HeaderName::from_static(request_signing::HEADER_SIGNATURE),
+                axum::http::HeaderName::from_static("x-wallet-password"),
+                axum::http::HeaderName::from_static(request_id::REQUEST_ID_HEADER),
             ])
             .expose_headers([
                 axum::http::header::CONTENT_TYPE,
                 axum::http::header::AUTHORIZATION,
+                axum::http::HeaderName::from_static(request_id::REQUEST_ID_HEADER),
             ])
             .allow_credentials(true)
             .max_age(CORS_MAX_AGE);
         
         app.merge(auth_router)
             .merge(anomaly_router)
+            // 未显式声明缓存策略的响应一律 no-store
+            .layer(axum::middleware::from_fn(http_cache::no_store_by_default))
+            // 错误响应体补上错误目录里的 retryable
+            .layer(axum::middleware::from_fn(error_catalog::annotate_errors))
+            // 每个请求一个 X-Request-Id，错误响应体里带同一个值
+            .layer(axum::middleware::from_fn(request_id::assign_request_id))
             .layer(cors_layer) // ✅ 全局CORS
     }
 
-    pub async fn start(self) -> Result<(), anyhow::Error> {
-        // Security guard: prevent accidental enabling of bridge mocks in production
-        // unless explicitly allowed by env. This runs at startup and fails fast.
-        #[allow(unused_imports)]
-        use crate::blockchain::bridge::relay::{
-            bridge_mocks_allowed, bridge_mocks_requested_truthy,
+    /// Background balance snapshot job sharing this server's clients and state.
+    pub fn balance_snapshotter(&self) -> BalanceSnapshotter {
+        let snapshotter = BalanceSnapshotter::new(
+            self.config.balance_snapshots.clone(),
+            self.user_db.clone(),
+            self.storage.clone(),
+            self.chain_clients.clone(),
+            self.maintenance.clone(),
+            self.circuit_breaker.clone(),
+            self.metrics.clone(),
+        );
+        let snapshotter = match &self.price_feed {
+            Some(feed) => snapshotter.with_pricing(feed.clone(), &self.config.pricing.default_currency),
+            None => snapshotter,
         };
-        #[cfg(not(feature = "test-env"))]
-        {
-            if bridge_mocks_requested_truthy() && !bridge_mocks_allowed() {
-                anyhow::bail!(
-                    "Bridge mocks requested via env (e.g. BRIDGE_MOCK_FORCE_SUCCESS=1), \
-but not allowed. Set ALLOW_BRIDGE_MOCKS=1 to enable in non-test runs, or unset mock envs."
-                );
-            }
-        }
-        let app = self.clone().create_router().await;
-        let addr = format!("{}:{}", self.host, self.port);
-        tracing::info!("Server listening on {}", addr);
-        let listener = TcpListener::bind(&addr).await?;
-        axum::serve(listener, app.into_make_service()).await?;
-        Ok(())
+        let lease = LeaderLease::for_scheduler(
+            self.storage.clone(),
+            BALANCE_SNAPSHOTS_JOB,
+            snapshotter.interval(),
+            Duration::from_secs(self.config.cluster.lease_grace_secs),
+        );
+        snapshotter.with_lease(lease)
     }
-}
 
-/// Helper to evaluate whether the startup mock guard would bail based on current env.
-/// Note: In test builds (`test-env` feature), bridge mocks are allowed so this will return false.
-pub fn startup_mock_guard_should_bail_for_env() -> bool {
-    use crate::blockchain::bridge::relay::{bridge_mocks_allowed, bridge_mocks_requested_truthy};
-    bridge_mocks_requested_truthy() && !bridge_mocks_allowed()
-}
+    /// Dead-man's switch evaluator sweeping through this server's signing path.
+    pub fn deadman_evaluator(&self) -> DeadmanEvaluator {
+        let evaluator = DeadmanEvaluator::new(
+            self.config.security.deadman.clone(),
+            self.storage.clone(),
+            self.wallet_manager.clone(),
+            self.gas_oracle.clone(),
+            self.signing_intents.clone(),
+            self.key_usage.clone(),
+            self.maintenance.clone(),
+        );
+        let lease = LeaderLease::for_scheduler(
+            self.storage.clone(),
+            DEADMAN_JOB,
+            evaluator.interval(),
+            Duration::from_secs(self.config.cluster.lease_grace_secs),
+        );
+        evaluator.with_lease(lease).with_flags(self.flags.clone())
+    }
 
-// shared request/response types are in crate::api::types
+    /// Receipt poller completing fee rows of this server's sends, expiring
+    /// sends stuck past their network's `pending_expiry_seconds`.
+    pub fn confirmation_poller(&self) -> ConfirmationPoller {
+        let expiry =
+            PendingExpiry::from_config(&self.config.blockchain, self.storage.clone(), self.signing_intents.chain())
+                .with_metrics(self.metrics.clone());
+        let poller = ConfirmationPoller::new(
+            self.config.fee_tracking.clone(),
+            self.storage.clone(),
+            self.signing_intents.chain(),
+        )
+        .with_expiry(expiry);
+        let lease = LeaderLease::for_scheduler(
+            self.storage.clone(),
+            FEE_CONFIRMATIONS_JOB,
+            poller.interval(),
+            Duration::from_secs(self.config.cluster.lease_grace_secs),
+        );
+        poller.with_lease(lease)
+    }
 
-#[cfg(test)]
-mod startup_guard_tests {
-    use super::startup_mock_guard_should_bail_for_env;
-    use std::env;
-
-    #[test]
-    fn test_startup_guard_bails_when_mocks_requested_without_allow() {
-        // Save env
-        let keys = [
-            "ALLOW_BRIDGE_MOCKS",
-            "BRIDGE_MOCK_FORCE_SUCCESS",
-            "BRIDGE_MOCK",
-            "FORCE_BRIDGE_SUCCESS",
-            "BRIDGE_MOCK_FORCE",
-        ];
-        let saved: Vec<(String, Option<String>)> =
-            keys.iter().map(|k| (k.to_string(), env::var(k).ok())).collect();
-        for k in &keys {
-            env::remove_var(k);
+    /// One block tracker per network listed under `fee_tracking.block_tracking`;
+    /// the confirmation poller leaves those networks alone.
+    pub fn block_trackers(&self) -> Vec<BlockTracker> {
+        let mut trackers = Vec::new();
+        for network in &self.config.fee_tracking.block_tracking.networks {
+            let Some(network_config) = self.config.blockchain.networks.get(network) else {
+                tracing::warn!("block tracking: network {} is not configured, skipping", network);
+                continue;
+            };
+            let tracker = BlockTracker::new(
+                network.clone(),
+                self.config.fee_tracking.clone(),
+                network_config.required_confirmations(),
+                self.storage.clone(),
+                self.signing_intents.chain(),
+            );
+            let lease = LeaderLease::for_scheduler(
+                self.storage.clone(),
+                &block_tracking::job_name(network),
+                tracker.interval(),
+                Duration::from_secs(self.config.cluster.lease_grace_secs),
+            );
+            trackers.push(tracker.with_lease(lease));
         }
+        trackers
+    }
 
-        // Request mocks but do not allow
-        env::set_var("BRIDGE_MOCK", "1");
+    /// Periodic gas oracle samples for the fee analytics market baseline.
+    pub fn gas_price_sampler(&self) -> GasPriceSampler {
+        let sampler = GasPriceSampler::new(
+            self.config.fee_tracking.clone(),
+            self.config.blockchain.networks.keys().cloned(),
+            self.gas_oracle.clone(),
+            self.storage.clone(),
+        );
+        let lease = LeaderLease::for_scheduler(
+            self.storage.clone(),
+            GAS_SAMPLES_JOB,
+            sampler.interval(),
+            Duration::from_secs(self.config.cluster.lease_grace_secs),
+        );
+        sampler.with_lease(lease)
+    }
 
-        // Under test builds with feature `test-env`, mocks are allowed and guard wouldn't bail.
-        // Also many test runners (and code coverage tools like tarpaulin) set
-        // `RUST_TEST_THREADS` which our bridge_mocks_allowed() treats as a
-        // signal to allow mocks. Avoid asserting the bail condition when
-        // either of those are present to prevent flaky CI failures.
-        if !cfg!(feature = "test-env") && std::env::var("RUST_TEST_THREADS").is_err() {
-            assert!(startup_mock_guard_should_bail_for_env());
-        }
+    /// Follows up on bridge transfers still initiated or in transit.
+    pub fn bridge_poller(&self) -> BridgePoller {
+        let poller =
+            BridgePoller::new(self.config.bridge_poller.clone(), self.storage.clone(), self.bridge_factory.clone());
+        let lease = LeaderLease::for_scheduler(
+            self.storage.clone(),
+            BRIDGE_POLLER_JOB,
+            poller.interval(),
+            Duration::from_secs(self.config.cluster.lease_grace_secs),
+        );
+        poller.with_lease(lease)
+    }
 
-        // Allow and confirm it does not bail
-        env::set_var("ALLOW_BRIDGE_MOCKS", "1");
-        assert!(!startup_mock_guard_should_bail_for_env());
+    /// Registers the background jobs enabled in the config with `self.jobs`.
+    pub fn register_jobs(&self) {
+        if self.config.balance_snapshots.enabled {
+            self.jobs.register(Arc::new(self.balance_snapshotter()));
+        }
+        if self.config.backups.enabled {
+            self.jobs.register(self.backups.clone());
+        }
+        if self.config.wal.enabled {
+            self.jobs.register(self.wal.clone());
+        }
+        if self.config.database_queries.pool_sample_interval_secs > 0 {
+            self.jobs.register(Arc::new(PoolStatsSampler::new(
+                self.storage.clone(),
+                self.metrics.clone(),
+                Duration::from_secs(self.config.database_queries.pool_sample_interval_secs),
+            )));
+        }
+        if self.config.fee_tracking.enabled {
+            self.jobs.register(Arc::new(self.confirmation_poller()));
+            self.jobs.register(Arc::new(self.gas_price_sampler()));
+            for tracker in self.block_trackers() {
+                self.jobs.register(Arc::new(tracker));
+            }
+        }
+        // `security.deadman.enabled` is the flag's default; wallets can still turn it on
+        self.jobs.register(Arc::new(self.deadman_evaluator()));
+        if self.config.backfill.enabled {
+            self.jobs.register(self.backfill.clone());
+        }
+        if self.config.bridge_poller.enabled {
+            self.jobs.register(Arc::new(self.bridge_poller()));
+        }
+        // idle unless a run is queued; the CLI reconciles without it
+        self.jobs.register(self.reconciliation.clone());
+        // only when main installed a non-env secret backend
+        let secrets = &self.config.security.secret_backend;
+        if let Some(store) = crate::security::env_manager::secret_backend::installed() {
+            if secrets.refresh_interval_secs > 0 {
+                self.jobs.register(Arc::new(
+                    crate::ops::secret_refresh::SecretRefresher::new(
+                        store,
+                        self.storage.clone(),
+                        Duration::from_secs(secrets.refresh_interval_secs),
+                        secrets.on_rotation,
+                    )
+                    .with_api_keys(self.api_key.clone()),
+                ));
+            }
+        }
+    }
 
-        // Restore envs
-        for (k, v) in saved {
-            match v {
-                Some(val) => env::set_var(k, val),
-                None => env::remove_var(k),
+    pub async fn start(self) -> Result<(), anyhow::Error> {
+        let app = self.clone().create_router().await;
+        let addr = format!("{}:{}", self.host, self.port);
+        tracing::info!("Server listening on {}", addr);
+        let listener = TcpListener::bind(&addr).await?;
+        // nothing is in flight yet, so every unresolved intent is a crash leftover
+        match self.signing_intents.reconcile(0).await {
+            Ok(report) if report.checked > 0 => tracing::warn!(
+                "Reconciled {} signing intents left by a previous run: {} recorded, {} released, {} errors",
+                report.checked,
+                report.recorded,
+                report.released,
+                report.errors
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Signing intent reconciliation failed: {}", e),
+        }
+        match self.storage.reconcile_pending_wallets(chrono::Duration::seconds(PENDING_WALLET_GRACE_SECS)).await {
+            Ok(report) if report.checked > 0 => tracing::warn!(
+                "Resolved {} wallets whose creation never completed: {} finalized, {} discarded",
+                report.checked,
+                report.finalized,
+                report.discarded
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Pending wallet reconciliation failed: {}", e),
+        }
+        // a bundle left `running` has no runner any more; it waits for an explicit resume
+        match self.storage.interrupt_running_bundles().await {
+            Ok(ids) if !ids.is_empty() => {
+                tracing::warn!("Marked {} operation bundles interrupted: {}", ids.len(), ids.join(", "))
             }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Interrupting running bundles failed: {}", e),
+        }
+        let version = handlers::system_info::collect_version(&self).await;
+        tracing::info!("{}", crate::build_info::banner(&version.build, &version.runtime));
+        // panics from here on land in `process_incidents`
+        incidents::install_panic_hook();
+        let incident_writer = incidents::attach(self.storage.clone(), self.metrics.clone());
+        self.register_jobs();
+        self.jobs.start();
+        let served = axum::serve(listener, app.into_make_service()).await;
+        // requests still queued for a signing slot fail now instead of waiting out their deadline
+        self.admission.drain();
+        // stop waiting on nonce leases; the job runner hands scheduler leases to the other instances right away
+        self.storage.begin_shutdown();
+        let drain = Duration::from_secs(self.config.jobs.drain_timeout_secs);
+        if !self.jobs.shutdown(drain).await {
+            tracing::warn!("Background jobs still running after {:?} were aborted", drain);
         }
+        // persist batched key usage counters before exiting
+        self.key_usage.shutdown().await;
+        incidents::detach();
+        let _ = tokio::time::timeout(Duration::from_secs(1), incident_writer).await;
+        served?;
+        Ok(())
     }
 }
+
+// shared request/response types are in crate::api::types
@@ -89,6 +89,14 @@ impl SessionStore {
         }
     }
     
+    /// 撤销user的全部token（账号擦除时调用），返回撤销数量
+    pub async fn revoke_user_tokens(&self, user_id: &str) -> usize {
+        let mut tokens = self.tokens.write().await;
+        let before = tokens.len();
+        tokens.retain(|_, info| info.user_id != user_id);
+        before - tokens.len()
+    }
+    
     /// 保存walletPassword到会话
     pub async fn cache_wallet_password(&self, user_id: &str, wallet_name: &str, password: &str) {
         let mut passwords = self.wallet_passwords.write().await;
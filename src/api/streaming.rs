@@ -0,0 +1,56 @@
+//! Incremental JSON response bodies.
+//!
+//! Large list responses are serialized one element at a time into the body
+//! stream, so peak memory is bounded by a single element rather than the
+//! fully rendered array.
+
+use axum::body::{Body, Bytes};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+
+/// Streams `items` as a JSON array. The output is byte-identical to
+/// `serde_json::to_vec(&items.collect::<Vec<_>>())`.
+pub fn json_array_body<T, I>(items: I) -> Body
+where
+    T: Serialize + Send + 'static,
+    I: IntoIterator<Item = T>,
+    I::IntoIter: Send + 'static,
+{
+    let elements = stream::iter(items.into_iter().enumerate()).map(|(i, item)| {
+        let mut buf = Vec::with_capacity(256);
+        if i > 0 {
+            buf.push(b',');
+        }
+        serde_json::to_writer(&mut buf, &item)?;
+        Ok::<Bytes, serde_json::Error>(Bytes::from(buf))
+    });
+
+    let body = stream::once(async { Ok(Bytes::from_static(b"[")) })
+        .chain(elements)
+        .chain(stream::once(async { Ok(Bytes::from_static(b"]")) }));
+
+    Body::from_stream(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn collect(body: Body) -> Vec<u8> {
+        axum::body::to_bytes(body, usize::MAX).await.unwrap().to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_json_array_body_matches_serde() {
+        let items: Vec<serde_json::Value> = (0..50)
+            .map(|i| serde_json::json!({ "id": i, "name": format!("w{}", i), "tags": ["a", "b"] }))
+            .collect();
+        let expected = serde_json::to_vec(&items).unwrap();
+        assert_eq!(collect(json_array_body(items)).await, expected);
+    }
+
+    #[tokio::test]
+    async fn test_json_array_body_empty() {
+        assert_eq!(collect(json_array_body(Vec::<u8>::new())).await, b"[]".to_vec());
+    }
+}
@@ -2,18 +2,29 @@
 
 use super::oneinch::OneInchClient;
 use super::types::*;
+use crate::api::error_catalog::ApiErrorCode;
 use crate::api::server::WalletServer;
-use crate::api::types::ErrorResponse;
+use crate::api::types::ApiError;
+use crate::core::amount::{Amount, AssetTag};
 use crate::core::errors::WalletError;
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
     response::{IntoResponse, Response},
     Json,
 };
 use std::sync::Arc;
 use tracing::{error, info};
 
+/// 代币数量的小数位（目前按 18 位处理）
+const TOKEN_DECIMALS: u32 = 18;
+
+/// 把用户输入的数量精确解析为最小单位；0 或格式错误返回 `None`
+fn parse_swap_amount(amount: &str, network: &str, token: &str) -> Option<Amount> {
+    Amount::parse(amount.trim(), TOKEN_DECIMALS, AssetTag::new(network, token.to_uppercase()))
+        .ok()
+        .filter(|a| !a.is_zero())
+}
+
 /// GET /api/swap/quote
 /// 
 /// fetch DEX 交换报价
@@ -28,27 +39,15 @@ pub async fn swap_quote(
 
     // validate输入
     if params.from.trim().is_empty() || params.to.trim().is_empty() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "源代币和目标代币不能为空".to_string(),
-                code: "INVALID_INPUT".to_string(),
-            }),
-        )
+        return ApiError::new(ApiErrorCode::InvalidInput, "源代币和目标代币不能为空")
             .into_response();
     }
 
     // 解析数量
-    let amount_f64 = match params.amount.parse::<f64>() {
-        Ok(v) if v > 0.0 => v,
-        _ => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: "无效的交换数量".to_string(),
-                    code: "INVALID_AMOUNT".to_string(),
-                }),
-            )
+    let amount = match parse_swap_amount(&params.amount, &params.network, &params.from) {
+        Some(amount) => amount,
+        None => {
+            return ApiError::new(ApiErrorCode::InvalidAmount, "无效的交换数量")
                 .into_response();
         }
     };
@@ -57,13 +56,7 @@ pub async fn swap_quote(
     let chain_id = match network_to_chain_id(&params.network) {
         Some(id) => id,
         None => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("不支持的network: {}", params.network),
-                    code: "UNSUPPORTED_NETWORK".to_string(),
-                }),
-            )
+            return ApiError::new(ApiErrorCode::UnsupportedNetwork, format!("不支持的network: {}", params.network))
                 .into_response();
         }
     };
@@ -72,13 +65,7 @@ pub async fn swap_quote(
     let from_token_addr = match get_token_address(&params.from, chain_id) {
         Some(addr) => addr,
         None => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("不支持的源代币: {}", params.from),
-                    code: "UNSUPPORTED_TOKEN".to_string(),
-                }),
-            )
+            return ApiError::new(ApiErrorCode::UnsupportedToken, format!("不支持的源代币: {}", params.from))
                 .into_response();
         }
     };
@@ -86,21 +73,12 @@ pub async fn swap_quote(
     let to_token_addr = match get_token_address(&params.to, chain_id) {
         Some(addr) => addr,
         None => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("不支持的目标代币: {}", params.to),
-                    code: "UNSUPPORTED_TOKEN".to_string(),
-                }),
-            )
+            return ApiError::new(ApiErrorCode::UnsupportedToken, format!("不支持的目标代币: {}", params.to))
                 .into_response();
         }
     };
 
-    // 转换数量为最小单位（假设18位小数）
-    let decimals = 18u32;
-    let amount_wei = (amount_f64 * 10f64.powi(decimals as i32)) as u128;
-    let amount_str = amount_wei.to_string();
+    let amount_str = amount.minimal().to_string();
 
     // 创建 1inch 客户端
     let api_key = super::oneinch::get_oneinch_api_key();
@@ -109,7 +87,7 @@ pub async fn swap_quote(
     // 如果没有 API Key，使用 Mock 数据
     if api_key.is_none() {
         info!("1inch API Key 未配置，使用 Mock 报价");
-        return Json(create_mock_quote(&params, amount_f64)).into_response();
+        return Json(create_mock_quote(&params, &amount)).into_response();
     }
 
     // 调用 1inch API
@@ -128,7 +106,7 @@ pub async fn swap_quote(
             error!("fetch交换报价failed: {:?}", e);
             // 降级到 Mock 数据
             info!("降级到 Mock 报价");
-            Json(create_mock_quote(&params, amount_f64)).into_response()
+            Json(create_mock_quote(&params, &amount)).into_response()
         }
     }
 }
@@ -140,6 +118,18 @@ pub async fn swap_execute(
     State(state): State<Arc<WalletServer>>,
     Json(req): Json<SwapExecuteRequest>,
 ) -> Response {
+    match execute_swap(&state, &req).await {
+        Ok(response) => Json(response).into_response(),
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+
+/// 交换执行本体；operation bundle 的 swap 步骤也走这里
+pub(crate) async fn execute_swap(
+    state: &WalletServer,
+    req: &SwapExecuteRequest,
+) -> Result<SwapExecuteResponse, ApiError> {
     info!(
         "收到交换执行请求: wallet={} 数量={} {} -> {}",
         req.wallet_name, req.amount, req.from_token, req.to_token
@@ -147,120 +137,44 @@ pub async fn swap_execute(
 
     // validate输入
     if req.wallet_name.trim().is_empty() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Wallet name不能为空".to_string(),
-                code: "INVALID_INPUT".to_string(),
-            }),
-        )
-            .into_response();
+        return Err(ApiError::new(ApiErrorCode::InvalidInput, "Wallet name不能为空"));
     }
 
     // validate数量
-    let amount_f64 = match req.amount.parse::<f64>() {
-        Ok(v) if v > 0.0 => v,
-        _ => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: "无效的交换数量".to_string(),
-                    code: "INVALID_AMOUNT".to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
+    let amount = parse_swap_amount(&req.amount, &req.network, &req.from_token)
+        .ok_or_else(|| ApiError::new(ApiErrorCode::InvalidAmount, "无效的交换数量"))?;
 
     // validate滑点
     if req.slippage < 0.0 || req.slippage > 50.0 {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "滑点必须在 0-50% 之间".to_string(),
-                code: "INVALID_SLIPPAGE".to_string(),
-            }),
-        )
-            .into_response();
+        return Err(ApiError::new(ApiErrorCode::InvalidSlippage, "滑点必须在 0-50% 之间"));
     }
 
     // checkwallet是否存在（尝试fetchwallet信息）
     if let Err(e) = state.wallet_manager.get_wallet_by_name(&req.wallet_name).await {
         error!("wallet '{}' 不存在或无法访问: {:?}", req.wallet_name, e);
-        return (
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("wallet '{}' 不存在", req.wallet_name),
-                code: "WALLET_NOT_FOUND".to_string(),
-            }),
-        )
-            .into_response();
+        return Err(ApiError::new(ApiErrorCode::WalletNotFound, format!("wallet '{}' 不存在", req.wallet_name)));
     }
 
     // fetchChain ID
-    let chain_id = match network_to_chain_id(&req.network) {
-        Some(id) => id,
-        None => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("不支持的network: {}", req.network),
-                    code: "UNSUPPORTED_NETWORK".to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
+    let chain_id = network_to_chain_id(&req.network).ok_or_else(|| {
+        ApiError::new(ApiErrorCode::UnsupportedNetwork, format!("不支持的network: {}", req.network))
+    })?;
 
     // fetch代币address
-    let from_token_addr = match get_token_address(&req.from_token, chain_id) {
-        Some(addr) => addr,
-        None => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("不支持的源代币: {}", req.from_token),
-                    code: "UNSUPPORTED_TOKEN".to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
-
-    let to_token_addr = match get_token_address(&req.to_token, chain_id) {
-        Some(addr) => addr,
-        None => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("不支持的目标代币: {}", req.to_token),
-                    code: "UNSUPPORTED_TOKEN".to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
+    let from_token_addr = get_token_address(&req.from_token, chain_id).ok_or_else(|| {
+        ApiError::new(ApiErrorCode::UnsupportedToken, format!("不支持的源代币: {}", req.from_token))
+    })?;
+    let to_token_addr = get_token_address(&req.to_token, chain_id).ok_or_else(|| {
+        ApiError::new(ApiErrorCode::UnsupportedToken, format!("不支持的目标代币: {}", req.to_token))
+    })?;
 
     // fetchwalletaddress
-    let wallet_address = match get_wallet_address(&state, &req.wallet_name, &req.network).await {
-        Ok(addr) => addr,
-        Err(e) => {
-            error!("fetchwalletaddressfailed: {:?}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "fetchwalletaddressfailed".to_string(),
-                    code: "WALLET_ADDRESS_FAILED".to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
+    let wallet_address = get_wallet_address(state, &req.wallet_name, &req.network).await.map_err(|e| {
+        error!("fetchwalletaddressfailed: {:?}", e);
+        ApiError::new(ApiErrorCode::WalletAddressFailed, "fetchwalletaddressfailed")
+    })?;
 
-    // 转换数量为最小单位
-    let decimals = 18u32;
-    let amount_wei = (amount_f64 * 10f64.powi(decimals as i32)) as u128;
-    let amount_str = amount_wei.to_string();
+    let amount_str = amount.minimal().to_string();
 
     // 创建 1inch 客户端
     let api_key = super::oneinch::get_oneinch_api_key();
@@ -268,8 +182,7 @@ pub async fn swap_execute(
     // 如果没有 API Key，返回 Mock 响应
     if api_key.is_none() {
         info!("1inch API Key 未配置，返回 Mock 交换结果");
-        let mock_response = create_mock_execute_response(&req, amount_f64);
-        return Json(mock_response).into_response();
+        return Ok(create_mock_execute_response(req, &amount));
     }
 
     let client = OneInchClient::new(api_key);
@@ -291,8 +204,7 @@ pub async fn swap_execute(
             error!("fetch交换transaction数据failed: {:?}", e);
             // 降级到 Mock
             info!("降级到 Mock 交换");
-            let mock_response = create_mock_execute_response(&req, amount_f64);
-            return Json(mock_response).into_response();
+            return Ok(create_mock_execute_response(req, &amount));
         }
     };
 
@@ -315,38 +227,35 @@ pub async fn swap_execute(
     {
         Ok(tx_hash) => {
             info!("交换transaction已发送: {}", tx_hash);
-            let response = SwapExecuteResponse {
+            Ok(SwapExecuteResponse {
                 tx_id: tx_hash.clone(),
                 status: "pending".to_string(),
                 from_amount: req.amount.clone(),
-                to_amount: calculate_to_amount(amount_f64, &req.from_token, &req.to_token),
+                to_amount: calculate_to_amount(&amount, &req.from_token, &req.to_token),
                 actual_rate: calculate_rate(&req.from_token, &req.to_token),
                 gas_used: Some("0.002".to_string()),
                 confirmations: 0,
-            };
-            Json(response).into_response()
+            })
         }
         Err(e) => {
             error!("交换transactionfailed: {:?}", e);
             // 降级到 Mock
-            let mock_response = create_mock_execute_response(&req, amount_f64);
-            Json(mock_response).into_response()
+            Ok(create_mock_execute_response(req, &amount))
         }
     }
 }
 
 /// 创建 Mock 报价
-fn create_mock_quote(req: &SwapQuoteRequest, amount: f64) -> SwapQuote {
-    let rate = calculate_rate(&req.from, &req.to);
-    let to_amount = amount * rate;
+fn create_mock_quote(req: &SwapQuoteRequest, amount: &Amount) -> SwapQuote {
+    let large = Amount::parse("10", TOKEN_DECIMALS, amount.asset().clone()).is_ok_and(|ten| *amount > ten);
 
     SwapQuote {
         from_token: req.from.clone(),
         to_token: req.to.clone(),
         from_amount: req.amount.clone(),
-        to_amount: format!("{:.6}", to_amount),
-        exchange_rate: rate,
-        price_impact: if amount > 10.0 { 0.5 } else { 0.15 },
+        to_amount: calculate_to_amount(amount, &req.from, &req.to),
+        exchange_rate: calculate_rate(&req.from, &req.to),
+        price_impact: if large { 0.5 } else { 0.15 },
         route: vec![RouteStep {
             protocol: "Mock DEX".to_string(),
             from: req.from.clone(),
@@ -361,46 +270,55 @@ fn create_mock_quote(req: &SwapQuoteRequest, amount: f64) -> SwapQuote {
 }
 
 /// 创建 Mock 执行响应
-fn create_mock_execute_response(req: &SwapExecuteRequest, amount: f64) -> SwapExecuteResponse {
-    let rate = calculate_rate(&req.from_token, &req.to_token);
-    let to_amount = amount * rate;
-
+fn create_mock_execute_response(req: &SwapExecuteRequest, amount: &Amount) -> SwapExecuteResponse {
     SwapExecuteResponse {
         tx_id: format!("0x{:x}{:x}", rand::random::<u64>(), rand::random::<u64>()),
         status: "confirmed".to_string(),
         from_amount: req.amount.clone(),
-        to_amount: format!("{:.6}", to_amount),
-        actual_rate: rate,
+        to_amount: calculate_to_amount(amount, &req.from_token, &req.to_token),
+        actual_rate: calculate_rate(&req.from_token, &req.to_token),
         gas_used: Some("0.00185".to_string()),
         confirmations: 12,
     }
 }
 
-/// 计算 Mock 汇率
-fn calculate_rate(from: &str, to: &str) -> f64 {
+/// Mock 汇率，以分数（分子, 分母）表示，换算数量时不经过浮点
+fn mock_rate(from: &str, to: &str) -> (u128, u128) {
     match (from.to_uppercase().as_str(), to.to_uppercase().as_str()) {
-        ("ETH", "USDT") | ("ETH", "USDC") => 3500.0,
-        ("USDT", "ETH") | ("USDC", "ETH") => 1.0 / 3500.0,
-        ("BTC", "USDT") | ("BTC", "USDC") => 65000.0,
-        ("USDT", "BTC") | ("USDC", "BTC") => 1.0 / 65000.0,
-        ("BNB", "USDT") | ("BNB", "USDC") => 580.0,
-        ("USDT", "BNB") | ("USDC", "BNB") => 1.0 / 580.0,
-        ("USDT", "USDC") | ("USDC", "USDT") => 1.0,
-        ("ETH", "BTC") => 3500.0 / 65000.0,
-        ("BTC", "ETH") => 65000.0 / 3500.0,
-        _ => 1.0,
+        ("ETH", "USDT") | ("ETH", "USDC") => (3500, 1),
+        ("USDT", "ETH") | ("USDC", "ETH") => (1, 3500),
+        ("BTC", "USDT") | ("BTC", "USDC") => (65000, 1),
+        ("USDT", "BTC") | ("USDC", "BTC") => (1, 65000),
+        ("BNB", "USDT") | ("BNB", "USDC") => (580, 1),
+        ("USDT", "BNB") | ("USDC", "BNB") => (1, 580),
+        ("USDT", "USDC") | ("USDC", "USDT") => (1, 1),
+        ("ETH", "BTC") => (3500, 65000),
+        ("BTC", "ETH") => (65000, 3500),
+        _ => (1, 1),
     }
 }
 
-/// 计算目标代币数量
-fn calculate_to_amount(amount: f64, from: &str, to: &str) -> String {
-    let rate = calculate_rate(from, to);
-    format!("{:.6}", amount * rate)
+/// 计算 Mock 汇率（仅用于展示）
+fn calculate_rate(from: &str, to: &str) -> f64 {
+    let (numerator, denominator) = mock_rate(from, to);
+    numerator as f64 / denominator as f64
+}
+
+/// 计算目标代币数量（精确，向下取整到最小单位）
+fn calculate_to_amount(amount: &Amount, from: &str, to: &str) -> String {
+    let (numerator, denominator) = mock_rate(from, to);
+    match amount.mul_div_floor(numerator, denominator) {
+        Ok(to_amount) => to_amount.to_decimal_string(),
+        Err(e) => {
+            error!("换算目标代币数量failed: {}", e);
+            "0".to_string()
+        }
+    }
 }
 
 /// fetchwalletaddress（辅助函数）
 async fn get_wallet_address(
-    _state: &Arc<WalletServer>,
+    _state: &WalletServer,
     _wallet_name: &str,
     _network: &str,
 ) -> Result<String, WalletError> {
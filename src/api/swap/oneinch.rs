@@ -1,6 +1,7 @@
 //! 1inch Aggregation API 集成
 
 use super::types::*;
+use crate::core::amount::{Amount, AssetTag};
 use crate::core::errors::WalletError;
 use reqwest::Client;
 use serde::Deserialize;
@@ -194,16 +195,16 @@ impl OneInchClient {
         to_symbol: &str,
         from_amount: &str,
     ) -> Result<SwapQuote, WalletError> {
-        // 解析数量
+        // 解析数量（1inch 的数量都是最小单位整数）
         let to_amount = data.to_amount.clone();
-        let from_amount_f64 = from_amount.parse::<f64>()
+        let from_minimal = from_amount.parse::<u128>()
             .map_err(|_| WalletError::InvalidInput("无效的源代币数量".to_string()))?;
-        let to_amount_f64 = to_amount.parse::<f64>()
+        let to_minimal = to_amount.parse::<u128>()
             .map_err(|_| WalletError::InvalidInput("无效的目标代币数量".to_string()))?;
 
-        // 计算汇率
-        let exchange_rate = if from_amount_f64 > 0.0 {
-            to_amount_f64 / from_amount_f64
+        // 计算汇率（仅用于展示）
+        let exchange_rate = if from_minimal > 0 {
+            to_minimal as f64 / from_minimal as f64
         } else {
             0.0
         };
@@ -241,13 +242,16 @@ impl OneInchClient {
             .unwrap_or_else(|| "150000".to_string());
         
         // 估算 Gas 费用（USD）- 简化计算
-        let gas_f64 = gas_estimate.parse::<f64>().unwrap_or(150000.0);
-        let estimated_gas_usd = (gas_f64 * 0.00000003 * 1900.0).max(0.1); // 粗略估算
-
-        // 价格影响（简化：根据数量估算）
-        let price_impact = if from_amount_f64 > 10.0 {
+        let gas_units = gas_estimate.parse::<u64>().unwrap_or(150000);
+        let estimated_gas_usd = (gas_units as f64 * 0.00000003 * 1900.0).max(0.1); // 粗略估算
+
+        // 价格影响（简化：根据主单位数量估算，按 18 位小数）
+        let asset = AssetTag::new("", from_symbol);
+        let from = Amount::new(from_minimal, 18, asset.clone());
+        let whole = |n: u128| Amount::new(n * 10u128.pow(18), 18, asset.clone());
+        let price_impact = if from > whole(10) {
             0.5
-        } else if from_amount_f64 > 1.0 {
+        } else if from > whole(1) {
             0.1
         } else {
             0.05
@@ -1,10 +1,109 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::middleware::request_id::current_request_id;
+use crate::api::middleware::request_metrics::{note_error, note_wallet_error};
+
+use crate::api::validators::{
+    parse_derivation_path, Amount, EvmAddress, NetworkName, ParamError, TokenUnits, Validate, WalletNameParam,
+};
+use crate::core::error_class::ErrorClass;
+use crate::core::errors::WalletError;
+use crate::core::validation::eip681::parse_payment_uri;
+use crate::core::wallet_manager::backup::ShareEnvelope;
+use crate::core::wallet_manager::derivation::DerivationPath;
+use crate::core::wallet_manager::NetworkInitStatus;
+use crate::operations::{BundleError, BundleStep, FailurePolicy, MAX_BUNDLE_STEPS};
+use crate::security::error_sanitizer::{sanitize_error_message, sanitize_for_logging};
+use crate::security::redaction::{contains_secret, is_sensitive_field};
+use crate::storage::{is_valid_metadata_key, MAX_DESCRIPTION_BYTES, MAX_METADATA_BYTES, MAX_METADATA_KEYS};
 
 /// queryaddress请求参数
 #[derive(Debug, Deserialize)]
 pub struct AddressQuery {
     /// Network type（ethereum, bitcoin等）- 可选，默认为eth
     pub network: Option<String>,
+    /// BIP32 派生路径（如 m/44'/60'/0'/0/5）- 可选，需要wallet Password
+    pub derivation_path: Option<String>,
+}
+
+/// `?network=` 可选参数（validate后），未提供时为 `None`
+#[derive(Debug, Clone, Copy)]
+pub struct OptionalNetworkQuery {
+    pub network: Option<NetworkName>,
+}
+
+impl Validate for OptionalNetworkQuery {
+    type Raw = AddressQuery;
+
+    fn validate(raw: AddressQuery) -> Result<Self, ParamError> {
+        let network = raw.network.as_deref().map(NetworkName::try_from).transpose()?;
+        Ok(Self { network })
+    }
+}
+
+/// `GET /api/wallets/:name/addresses` 参数（validate后）
+#[derive(Debug, Clone)]
+pub struct AddressParams {
+    pub network: Option<NetworkName>,
+    pub derivation_path: Option<DerivationPath>,
+}
+
+impl Validate for AddressParams {
+    type Raw = AddressQuery;
+
+    fn validate(raw: AddressQuery) -> Result<Self, ParamError> {
+        let network = raw.network.as_deref().map(NetworkName::try_from).transpose()?;
+        let derivation_path = raw.derivation_path.as_deref().map(parse_derivation_path).transpose()?;
+        Ok(Self { network, derivation_path })
+    }
+}
+
+/// 必填 `?network=` 参数；balancequery另可带 `?token_address=` 查 ERC-20 balance
+#[derive(Debug, Deserialize)]
+pub struct BalanceQuery {
+    pub network: String,
+    #[serde(default)]
+    pub token_address: Option<String>,
+}
+
+/// [`BalanceQuery`] validate后
+#[derive(Debug, Clone, Copy)]
+pub struct NetworkQuery {
+    pub network: NetworkName,
+}
+
+impl Validate for NetworkQuery {
+    type Raw = BalanceQuery;
+
+    fn validate(raw: BalanceQuery) -> Result<Self, ParamError> {
+        Ok(Self { network: NetworkName::try_from(raw.network.as_str())? })
+    }
+}
+
+/// `GET /api/wallets/:name/balance` 参数（validate后）；token 只在 EVM network上有意义
+#[derive(Debug, Clone)]
+pub struct BalanceParams {
+    pub network: NetworkName,
+    pub token_address: Option<EvmAddress>,
+}
+
+impl Validate for BalanceParams {
+    type Raw = BalanceQuery;
+
+    fn validate(raw: BalanceQuery) -> Result<Self, ParamError> {
+        let network = NetworkName::try_from(raw.network.as_str())?;
+        let token_address = raw.token_address.as_deref().map(EvmAddress::try_from).transpose()?;
+        if token_address.is_some() {
+            network.require_evm()?;
+        }
+        Ok(Self { network, token_address })
+    }
 }
 
 /// address响应
@@ -14,6 +113,9 @@ pub struct AddressResponse {
     pub address: String,
     /// Network type
     pub network: String,
+    /// 按请求的派生路径推导时回显规范化的路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -41,6 +143,16 @@ pub struct CreateWalletRequest {
     /// 多签配置（仅当wallet_type为"multisig"时需要）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub multisig_config: Option<MultiSigConfig>,
+    /// 创建时一并初始化的network（address、nonce、充值扫描游标）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub initialize_networks: Vec<String>,
+    /// BIP32 派生路径（可选，如 m/44'/60'/0'/0/5），记为该wallet的默认路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+    /// BIP39 passphrase（可选）。非托管模式下由前端在派生 `wallet_address` 时使用，
+    /// 服务器不派生也不保存；传入非空值会被拒绝，以免误以为服务器已应用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
 }
 
 /// 多签wallet配置
@@ -83,6 +195,150 @@ pub struct WalletResponse {
     /// Warning信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub warning: Option<String>,
+    /// 创建时的资金预检结果（仅在 `?preflight=` 时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preflight: Option<Vec<NetworkPreflight>>,
+    /// 各network的初始化状态（仅在请求了 `initialize_networks` 时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub networks: Option<Vec<NetworkInitStatus>>,
+    /// wallet描述（未设置时省略）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 自定义元数据（未设置时省略）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// wallet列表中的单条记录：字段与 [`WalletResponse`] 的序列化结果一致，
+/// 但借用底层数据，逐条序列化时不复制字符串
+#[derive(Serialize)]
+pub struct WalletListItem<'a> {
+    pub id: Cow<'a, str>,
+    pub name: Cow<'a, str>,
+    pub address: Cow<'a, str>,
+    pub quantum_safe: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wallet_type: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<&'a serde_json::Map<String, serde_json::Value>>,
+    /// 省略表示所有network均可用（[`WalletResponse`] 没有该字段）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_networks: Option<Vec<String>>,
+}
+
+/// `GET /api/wallets?cursor=&limit=&search=`
+#[derive(Debug, Default, Deserialize)]
+pub struct WalletListQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    /// 在名称、描述和元数据值中做不区分大小写的子串匹配
+    pub search: Option<String>,
+}
+
+/// `PATCH /api/wallets/:name`
+///
+/// `metadata` 按 JSON merge patch 合并：值为 null 删除该键，未出现的键保持不变；
+/// `description` 缺省不变，为 null 时清空
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateWalletNotesRequest {
+    #[serde(default, deserialize_with = "crate::api::user_preferences::deserialize_double_option")]
+    pub description: Option<Option<String>>,
+    #[serde(default)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+/// [`UpdateWalletNotesRequest`] validate后：键名合法、值为标量、不含疑似密钥的内容
+#[derive(Debug, Clone, Default)]
+pub struct WalletNotesPatch {
+    pub description: Option<Option<String>>,
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Validate for WalletNotesPatch {
+    type Raw = UpdateWalletNotesRequest;
+
+    fn validate(raw: UpdateWalletNotesRequest) -> Result<Self, ParamError> {
+        // 空描述等同于清空
+        let description = raw.description.map(|d| d.filter(|d| !d.trim().is_empty()));
+        if let Some(Some(d)) = &description {
+            if d.len() > MAX_DESCRIPTION_BYTES {
+                return Err(ParamError::NotesTooLarge(format!(
+                    "description is {} bytes, at most {} allowed",
+                    d.len(),
+                    MAX_DESCRIPTION_BYTES
+                )));
+            }
+            if contains_secret(d) {
+                return Err(ParamError::NotesSecret("description".to_string()));
+            }
+        }
+
+        for (key, value) in &raw.metadata {
+            if !is_valid_metadata_key(key) {
+                return Err(ParamError::WalletNotes(format!(
+                    "metadata key {:?} must be 1-64 characters of a-z, 0-9, '_' and '.'",
+                    key
+                )));
+            }
+            if is_sensitive_field(key) {
+                return Err(ParamError::NotesSecret(format!("metadata key {}", key)));
+            }
+            match value {
+                serde_json::Value::Null | serde_json::Value::Bool(_) | serde_json::Value::Number(_) => {}
+                serde_json::Value::String(s) if contains_secret(s) => {
+                    return Err(ParamError::NotesSecret(format!("metadata.{}", key)));
+                }
+                serde_json::Value::String(_) => {}
+                _ => {
+                    return Err(ParamError::WalletNotes(format!(
+                        "metadata.{} must be a string, number, boolean or null",
+                        key
+                    )))
+                }
+            }
+        }
+        // 合并后的上限由存储层在事务内检查；这里先挡住单个 patch 就已超限的请求
+        if raw.metadata.values().filter(|v| !v.is_null()).count() > MAX_METADATA_KEYS {
+            return Err(ParamError::MetadataKeys(MAX_METADATA_KEYS));
+        }
+        let size = serde_json::to_string(&raw.metadata).map(|s| s.len()).unwrap_or(usize::MAX);
+        if size > MAX_METADATA_BYTES {
+            return Err(ParamError::NotesTooLarge(format!(
+                "metadata is {} bytes, at most {} allowed",
+                size, MAX_METADATA_BYTES
+            )));
+        }
+        Ok(Self { description, metadata: raw.metadata })
+    }
+}
+
+/// `PATCH /api/wallets/:name` 的响应：合并后的完整描述与元数据
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletNotesResponse {
+    pub wallet_name: String,
+    pub description: Option<String>,
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 单个network的资金预检：address、当前balance、建议gas price、一次标准转账所需最小金额
+///
+/// RPC 调用失败或超时时对应字段为 null，不影响wallet创建。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NetworkPreflight {
+    pub network: String,
+    pub address: Option<String>,
+    pub symbol: Option<String>,
+    /// 当前balance（原生币单位，预期为 0）
+    pub balance: Option<String>,
+    /// 建议gas price（wei）
+    pub gas_price_wei: Option<String>,
+    /// 完成一次标准转账所需的最小原生币金额
+    pub min_transfer_amount: Option<String>,
+    /// 该network无法预检时的error code（如 `UNSUPPORTED_NETWORK`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 // 默认值辅助函数
@@ -94,13 +350,18 @@ fn default_mnemonic_count() -> u32 {
     12
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct SendTransactionRequest {
     /// 目标address（优先使用 to，兼容 to_address）
-    #[serde(alias = "to_address")]
+    #[serde(default, alias = "to_address")]
     pub to: String,
+    #[serde(default)]
     pub amount: String,
+    #[serde(default)]
     pub network: String,
+    /// EIP-681 付款链接，可代替 to / amount / network；同时提供时必须一致
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "paymentUri")]
+    pub payment_uri: Option<String>,
     /// Password（托管模式使用，可选）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub password: Option<String>,
@@ -110,6 +371,191 @@ pub struct SendTransactionRequest {
     /// 幂等键（可选，防止重复提交）
     #[serde(skip_serializing_if = "Option::is_none", alias = "clientRequestId")]
     pub client_request_id: Option<String>,
+    /// 确认发送与近期未确认transaction完全相同的转账（默认拒绝）
+    #[serde(default, alias = "allowDuplicate")]
+    pub allow_duplicate: bool,
+    /// 接受没有 EIP-155 重放保护的 `signed_tx`（默认拒绝）
+    #[serde(default, alias = "allowUnprotected")]
+    pub allow_unprotected: bool,
+    /// 确认向合约address转账（收款方是代币合约或未知合约时默认拒绝）
+    #[serde(default, alias = "acknowledgeContractRecipient")]
+    pub acknowledge_contract_recipient: bool,
+    /// 只估算手续费与 nonce，不sign、不广播（仅托管模式）
+    #[serde(default, alias = "dryRun")]
+    pub dry_run: bool,
+}
+
+/// [`SendTransactionRequest`] validate后
+#[derive(Debug, Clone)]
+pub struct SendTransaction {
+    pub to: EvmAddress,
+    pub amount: Amount,
+    /// body中为空时为 `None`（可由 `?network=` 提供）
+    pub network: Option<NetworkName>,
+    pub password: Option<String>,
+    pub signed_tx: Option<String>,
+    pub client_request_id: Option<String>,
+    pub allow_duplicate: bool,
+    pub allow_unprotected: bool,
+    pub acknowledge_contract_recipient: bool,
+    pub dry_run: bool,
+    /// `payment_uri` 中的 chain id；handler 按已配置network解析并与 `network` 核对
+    pub payment_chain_id: Option<u64>,
+}
+
+impl Validate for SendTransaction {
+    type Raw = SendTransactionRequest;
+
+    fn validate(raw: SendTransactionRequest) -> Result<Self, ParamError> {
+        let (to, amount, payment_chain_id) = match raw.payment_uri.as_deref() {
+            Some(uri) => payment_uri_fields(uri, &raw.to, &raw.amount)?,
+            None => (EvmAddress::try_from(raw.to.as_str())?, Amount::try_from(raw.amount.as_str())?, None),
+        };
+        // 估算使用服务端的wallet，外部sign的transaction无从估算
+        if raw.dry_run && raw.signed_tx.is_some() {
+            return Err(ParamError::DryRun("needs a server-signed send (password), not signed_tx"));
+        }
+        // 目标为EVM address，btc 无意义
+        let network = match raw.network.as_str() {
+            "" => None,
+            n => Some(NetworkName::try_from(n)?.require_evm()?),
+        };
+        Ok(Self {
+            to,
+            amount,
+            network,
+            payment_chain_id,
+            password: raw.password,
+            signed_tx: raw.signed_tx,
+            client_request_id: raw.client_request_id,
+            allow_duplicate: raw.allow_duplicate,
+            allow_unprotected: raw.allow_unprotected,
+            acknowledge_contract_recipient: raw.acknowledge_contract_recipient,
+            dry_run: raw.dry_run,
+        })
+    }
+}
+
+/// 从 EIP-681 链接取收款address、金额与 chain id；body 里另给的 to / amount 必须与之一致
+fn payment_uri_fields(uri: &str, to: &str, amount: &str) -> Result<(EvmAddress, Amount, Option<u64>), ParamError> {
+    use crate::core::amount::{Amount as Minimal, AssetTag};
+
+    let intent = parse_payment_uri(uri).map_err(|e| ParamError::PaymentUri(e.to_string()))?;
+    if !intent.is_native() {
+        return Err(ParamError::PaymentUriUnsupported(
+            "ERC-20 transfer requests cannot be sent from this endpoint",
+        ));
+    }
+    let recipient = EvmAddress::try_from(ethers::utils::to_checksum(&intent.recipient, None).as_str())?;
+    if !to.is_empty() && !EvmAddress::try_from(to)?.as_str().eq_ignore_ascii_case(recipient.as_str()) {
+        return Err(ParamError::PaymentUriMismatch("to"));
+    }
+
+    // EVM 原生币都是 18 位小数
+    let to_wei = |amount: &Amount| Minimal::parse(amount.as_str(), 18, AssetTag::new("", "")).map(|a| a.minimal());
+    let amount = match (intent.amount, amount) {
+        (None, "") => return Err(ParamError::Missing("amount")),
+        (None, amount) => Amount::try_from(amount)?,
+        (Some(value), body) => {
+            if value > ethers::types::U256::from(u128::MAX) {
+                return Err(ParamError::AmountTooLong);
+            }
+            let value = value.as_u128();
+            let decimal = Minimal::new(value, 18, AssetTag::new("", "")).to_decimal_string();
+            if !body.is_empty() && to_wei(&Amount::try_from(body)?).ok() != Some(value) {
+                return Err(ParamError::PaymentUriMismatch("amount"));
+            }
+            Amount::try_from(decimal.as_str())?
+        }
+    };
+    Ok((recipient, amount, intent.chain_id))
+}
+
+/// 一次批量发送最多的transaction数
+pub const MAX_BATCH_SIZE: usize = 50;
+
+/// `POST /api/wallets/:name/send_batch`
+#[derive(Debug, Deserialize)]
+pub struct SendBatchRequest {
+    /// 按顺序发送；每项与 `/send` 的请求体相同（只支持托管模式）
+    #[serde(default)]
+    pub transactions: Vec<SendTransactionRequest>,
+    /// 第一笔失败后不再发送其余transaction（默认继续）
+    #[serde(default, alias = "failFast")]
+    pub fail_fast: bool,
+}
+
+/// [`SendBatchRequest`] validate后（不实现 Debug，避免Password进日志）
+pub struct SendBatch {
+    pub transactions: Vec<SendTransaction>,
+    /// 各项共用的walletPassword
+    pub password: String,
+    pub fail_fast: bool,
+}
+
+impl Validate for SendBatch {
+    type Raw = SendBatchRequest;
+
+    fn validate(raw: SendBatchRequest) -> Result<Self, ParamError> {
+        if raw.transactions.is_empty() {
+            return Err(ParamError::Missing("transactions"));
+        }
+        if raw.transactions.len() > MAX_BATCH_SIZE {
+            return Err(ParamError::Batch(format!("at most {} transactions per batch", MAX_BATCH_SIZE)));
+        }
+        let mut password: Option<String> = None;
+        let mut transactions = Vec::with_capacity(raw.transactions.len());
+        for (index, item) in raw.transactions.into_iter().enumerate() {
+            let item = SendTransaction::validate(item)
+                .map_err(|e| ParamError::Batch(format!("transactions[{}]: {}", index, e)))?;
+            // nonce 由服务端连续分配，外部sign的transaction无法加入
+            if item.signed_tx.is_some() {
+                return Err(ParamError::Batch(format!("transactions[{}]: signed_tx cannot be batched", index)));
+            }
+            if item.dry_run {
+                return Err(ParamError::Batch(format!("transactions[{}]: dry_run cannot be batched", index)));
+            }
+            match (&password, &item.password) {
+                (_, None) => return Err(ParamError::Batch(format!("transactions[{}]: password is required", index))),
+                (None, Some(p)) => password = Some(p.clone()),
+                (Some(first), Some(p)) if first != p => {
+                    return Err(ParamError::Batch(format!(
+                        "transactions[{}]: password differs from transactions[0]",
+                        index
+                    )))
+                }
+                _ => {}
+            }
+            transactions.push(item);
+        }
+        Ok(Self { transactions, password: password.unwrap_or_default(), fail_fast: raw.fail_fast })
+    }
+}
+
+/// 批量发送中一笔的结果，与请求同序
+#[derive(Debug, Default, Serialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    /// `sent`、`failed`，或 `skipped`（`fail_fast` 或前面已分配 nonce 的一笔失败后未发送）
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// 失败或跳过的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SendBatchResponse {
+    pub network: String,
+    pub results: Vec<BatchItemResult>,
+    /// 第一笔失败的下标；全部发送时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_failure: Option<usize>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -128,150 +574,2399 @@ pub struct TransactionResponse {
     pub fee: String,
     /// 确认数（字符串格式）
     pub confirmations: String,
+    /// 区块浏览器链接（network 配置了 `explorer_url_template` 时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+}
+
+/// `dry_run` 发送的估算结果；wei 金额为十进制字符串
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SendDryRunResponse {
+    pub dry_run: bool,
+    pub network: String,
+    pub from_address: String,
+    pub to: String,
+    pub amount: String,
+    pub estimated_gas: String,
+    pub gas_price: String,
+    pub total_fee_wei: String,
+    /// 金额加手续费
+    pub total_cost_wei: String,
+    /// 现在发送将使用的 nonce（未预留）
+    pub nonce_to_use: u64,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct SendTransactionResponse {
     pub tx_hash: String,
     pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct BridgeAssetsRequest {
-    pub from_wallet: String,
-    pub from_chain: String,
-    pub to_chain: String,
-    pub token: String,
-    pub amount: String,
-    /// 幂等键（可选，防止重复提交）
-    #[serde(skip_serializing_if = "Option::is_none", alias = "clientRequestId")]
-    pub client_request_id: Option<String>,
+/// 超过审查阈值的发送：202，等待第二人审批
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingApprovalResponse {
+    pub approval_id: String,
+    /// 固定为 `pending_approval`
+    pub status: String,
+    /// Unix 秒；过期后不可执行
+    pub expires_at: i64,
 }
 
-#[derive(Serialize)]
-pub struct BridgeResponse {
-    /// 桥接ID（前端期望）
-    pub bridge_id: String,
-    /// 桥接transactionID（兼容字段）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub bridge_tx_id: Option<String>,
-    /// 状态（新增，前端需要）
-    pub status: String,
-    /// 目标链（前端期望）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub target_chain: Option<String>,
-    /// 金额（前端期望）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub amount: Option<String>,
-    /// 源链（可选）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub from_chain: Option<String>,
-    /// 代币（可选）
+/// `GET /api/approvals`
+#[derive(Debug, Serialize)]
+pub struct ApprovalListResponse {
+    pub approvals: Vec<crate::storage::ApprovalRecord>,
+}
+
+/// `POST /api/approvals/:id/approve|reject`
+#[derive(Debug, Serialize)]
+pub struct ApprovalDecisionResponse {
+    pub approval: crate::storage::ApprovalRecord,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub token: Option<String>,
+    pub explorer_url: Option<String>,
 }
 
-#[derive(Serialize)]
-pub struct BridgeTransactionResponse {
-    pub id: String,
-    pub from_wallet: String,
-    pub from_chain: String,
-    pub to_chain: String,
-    pub token: String,
-    pub amount: String,
-    pub status: String,
-    pub source_tx_hash: Option<String>,
-    pub destination_tx_hash: Option<String>,
-    pub created_at: String,
-    pub updated_at: String,
-    pub fee_amount: Option<String>,
-    pub estimated_completion_time: Option<String>,
+/// `GET /api/approvals?status=pending&limit=50`
+#[derive(Debug, Deserialize)]
+pub struct ApprovalListQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
 }
 
-#[derive(Serialize)]
-pub struct BalanceResponse {
-    pub balance: String,
-    pub network: String,
-    pub symbol: String,
+/// [`ApprovalListQuery`] validate后
+#[derive(Debug, Clone)]
+pub struct ApprovalListParams {
+    pub status: Option<&'static str>,
+    pub limit: i64,
 }
 
-#[derive(Serialize)]
-pub struct TransactionHistoryResponse {
-    pub transactions: Vec<crate::blockchain::traits::TransactionInfo>,
+pub const MAX_APPROVAL_LIST_LIMIT: i64 = 200;
+
+impl Validate for ApprovalListParams {
+    type Raw = ApprovalListQuery;
+
+    fn validate(raw: ApprovalListQuery) -> Result<Self, ParamError> {
+        use crate::storage::{
+            APPROVAL_APPROVED, APPROVAL_EXPIRED, APPROVAL_FAILED, APPROVAL_PENDING, APPROVAL_REJECTED,
+        };
+        let status = raw
+            .status
+            .map(|status| {
+                [APPROVAL_PENDING, APPROVAL_APPROVED, APPROVAL_REJECTED, APPROVAL_EXPIRED, APPROVAL_FAILED]
+                    .into_iter()
+                    .find(|s| *s == status)
+                    .ok_or(ParamError::ApprovalStatus)
+            })
+            .transpose()?;
+        Ok(Self { status, limit: raw.limit.unwrap_or(50).clamp(1, MAX_APPROVAL_LIST_LIMIT) })
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct EncryptedBackupResponse {
-    /// Format version for the encrypted backup object
-    pub version: String,
-    /// Algorithm used, e.g. AES-256-GCM
-    pub alg: String,
-    /// KEK identifier used to encrypt this backup (optional)
-    pub kek_id: Option<String>,
-    /// Base64-encoded nonce
-    pub nonce: String,
-    /// Base64-encoded ciphertext (encrypted seed phrase)
-    pub ciphertext: String,
-    /// Wallet name for reference
-    pub wallet: String,
+/// `POST /api/approvals/:id/reject`
+#[derive(Debug, Deserialize)]
+pub struct RejectApprovalRequest {
+    pub reason: Option<String>,
 }
 
-// Backwards-compatible alias for handler usage in tests; production handlers should
-// return `EncryptedBackupResponse`. For test-env, we still allow returning plaintext
-// in the `ciphertext` field with `alg = "PLAINTEXT"` to preserve deterministic tests.
-pub type BackupResponse = EncryptedBackupResponse;
+pub const MAX_REJECT_REASON_LEN: usize = 500;
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct RestoreWalletRequest {
-    pub name: String,
-    pub seed_phrase: String,
-    #[serde(default)]
-    pub quantum_safe: bool,
-    /// BIP39Password短语（可选，用于额外保护）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub passphrase: Option<String>,
-    /// 指定network（可选："eth" | "btc" | "bsc" | "polygon"）
-    #[serde(skip_serializing_if = "Option::is_none")]
+/// [`RejectApprovalRequest`] validate后：非空、最多 500 字符
+#[derive(Debug, Clone)]
+pub struct RejectApproval {
+    pub reason: String,
+}
+
+impl Validate for RejectApproval {
+    type Raw = RejectApprovalRequest;
+
+    fn validate(raw: RejectApprovalRequest) -> Result<Self, ParamError> {
+        let reason =
+            raw.reason.as_deref().map(str::trim).filter(|r| !r.is_empty()).ok_or(ParamError::Missing("reason"))?;
+        if reason.chars().count() > MAX_REJECT_REASON_LEN {
+            return Err(ParamError::ReasonTooLong(MAX_REJECT_REASON_LEN));
+        }
+        Ok(Self { reason: reason.to_string() })
+    }
+}
+
+/// `PUT /api/wallets/:name/review_threshold`；`threshold: null` 取消审批要求
+#[derive(Debug, Deserialize)]
+pub struct ReviewThresholdRequest {
+    pub threshold: Option<String>,
+}
+
+/// [`ReviewThresholdRequest`] validate后
+#[derive(Debug, Clone)]
+pub struct ReviewThreshold {
+    pub threshold: Option<Amount>,
+}
+
+impl Validate for ReviewThreshold {
+    type Raw = ReviewThresholdRequest;
+
+    fn validate(raw: ReviewThresholdRequest) -> Result<Self, ParamError> {
+        let threshold = raw.threshold.as_deref().map(Amount::try_from).transpose()?;
+        // 之后按 wei 比较，必须能表示为 U256
+        if threshold.as_ref().is_some_and(|t| ethers::utils::parse_ether(t.as_str()).is_err()) {
+            return Err(ParamError::AmountTooLong);
+        }
+        Ok(Self { threshold })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReviewThresholdResponse {
+    pub wallet_name: String,
+    /// 整单位十进制；`None` 表示无需审批
+    pub threshold: Option<String>,
+}
+
+/// `PUT /api/wallets/:name/limits`：整单位十进制；两项都为 null 时删除该network的上限
+#[derive(Debug, Deserialize)]
+pub struct SpendingLimitRequest {
     pub network: Option<String>,
-    /// 自定义派生路径（可选，如 m/44'/60'/0'/0）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub derivation_path: Option<String>,
-    /// address类型（可选："btc_bech32" | "btc_p2sh" | "btc_legacy" | "evm"）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub address_type: Option<String>,
-    /// 起始索引（可选，默认0）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub start_index: Option<u32>,
-    /// 批量导入数量（可选，默认1）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub import_count: Option<u32>,
+    pub max_per_tx: Option<String>,
+    pub max_per_day: Option<String>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct MultiSigTransactionRequest {
-    /// 目标address（优先使用 to，兼容 to_address）
-    #[serde(alias = "to_address")]
-    pub to: String,
-    pub amount: String,
-    pub network: String,
-    pub signatures: Vec<String>,
+/// [`SpendingLimitRequest`] validate后，金额已换算为最小单位（wei）
+#[derive(Debug, Clone)]
+pub struct SpendingLimitUpdate {
+    pub network: NetworkName,
+    pub max_per_tx: Option<u128>,
+    pub max_per_day: Option<u128>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct MultiSigTransactionResponse {
-    pub tx_hash: String,
-    pub status: String,
+impl Validate for SpendingLimitUpdate {
+    type Raw = SpendingLimitRequest;
+
+    fn validate(raw: SpendingLimitRequest) -> Result<Self, ParamError> {
+        let network = raw.network.as_deref().ok_or(ParamError::NetworkMissing)?;
+        let network = NetworkName::try_from(network)?.require_evm()?;
+        let minimal = |value: Option<String>| -> Result<Option<u128>, ParamError> {
+            let Some(value) = value else {
+                return Ok(None);
+            };
+            let value = Amount::try_from(value.as_str())?;
+            crate::core::amount::Amount::parse_native(network.as_str(), value.as_str())
+                .map(|amount| Some(amount.minimal()))
+                .map_err(|_| ParamError::AmountTooLong)
+        };
+        let max_per_tx = minimal(raw.max_per_tx)?;
+        let max_per_day = minimal(raw.max_per_day)?;
+        if let (Some(per_tx), Some(per_day)) = (max_per_tx, max_per_day) {
+            if per_tx > per_day {
+                return Err(ParamError::SpendingLimit("max_per_tx is above max_per_day"));
+            }
+        }
+        Ok(Self { network, max_per_tx, max_per_day })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ErrorResponse {
-    pub error: String,
-    pub code: String,
+pub struct SpendingLimitsResponse {
+    pub wallet_name: String,
+    /// 按network排序；未设置上限的network不列出
+    pub limits: Vec<SpendingLimitView>,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
-pub struct RotateSigningKeyResponse {
-    pub wallet: String,
-    pub old_version: u32,
-    pub new_version: u32,
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpendingLimitView {
+    pub network: String,
+    /// 整单位十进制；`None` 表示不限
+    pub max_per_tx: Option<String>,
+    pub max_per_day: Option<String>,
+    /// 最近 24 小时内 pending 与 confirmed 发送的合计，整单位十进制
+    pub spent_last_day: String,
+    pub updated_by: String,
+    /// Unix 秒
+    pub updated_at: i64,
+}
+
+/// `PUT /api/wallets/:name/networks`；`allowed_networks: null` 取消限制
+#[derive(Debug, Deserialize)]
+pub struct AllowedNetworksRequest {
+    pub allowed_networks: Option<Vec<String>>,
+}
+
+/// [`AllowedNetworksRequest`] validate后：规范network名，去重保序
+#[derive(Debug, Clone)]
+pub struct AllowedNetworks {
+    pub allowed_networks: Option<Vec<NetworkName>>,
+}
+
+impl Validate for AllowedNetworks {
+    type Raw = AllowedNetworksRequest;
+
+    fn validate(raw: AllowedNetworksRequest) -> Result<Self, ParamError> {
+        let allowed_networks = match raw.allowed_networks {
+            None => None,
+            Some(names) => {
+                let mut networks: Vec<NetworkName> = Vec::new();
+                for name in &names {
+                    let network = NetworkName::try_from(name.as_str())?;
+                    if !networks.contains(&network) {
+                        networks.push(network);
+                    }
+                }
+                Some(networks)
+            }
+        };
+        Ok(Self { allowed_networks })
+    }
+}
+
+/// `POST /api/wallets/:name/nonces/reconcile`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct NonceReconcileRequest {
+    pub network: Option<String>,
+    /// 预留不足该秒数的 nonce 视为仍在广播途中，不归还；默认 [`DEFAULT_NONCE_MIN_AGE_SECS`]
+    pub min_age_secs: Option<u64>,
+}
+
+/// 未指定 `min_age_secs` 时的默认值
+pub const DEFAULT_NONCE_MIN_AGE_SECS: u64 = 300;
+
+/// [`NonceReconcileRequest`] validate后
+#[derive(Debug, Clone)]
+pub struct NonceReconcileParams {
+    pub network: NetworkName,
+    pub min_age_secs: u64,
+}
+
+impl Validate for NonceReconcileParams {
+    type Raw = NonceReconcileRequest;
+
+    fn validate(raw: NonceReconcileRequest) -> Result<Self, ParamError> {
+        let network = NetworkName::try_from(raw.network.as_deref().unwrap_or("eth"))?.require_evm()?;
+        let min_age_secs = raw.min_age_secs.unwrap_or(DEFAULT_NONCE_MIN_AGE_SECS);
+        if min_age_secs > i32::MAX as u64 {
+            return Err(ParamError::Reconciliation("min_age_secs is too large"));
+        }
+        Ok(Self { network, min_age_secs })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AllowedNetworksResponse {
+    pub wallet_name: String,
+    /// `None` 表示所有network均可用
+    pub allowed_networks: Option<Vec<String>>,
+    /// 被移除但已有transaction记录的network
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// `POST /api/groups`
+#[derive(Debug, Deserialize)]
+pub struct CreateWalletGroupRequest {
+    pub name: String,
+    /// admin（API key）请求需要；会话user请求忽略，owner 即本人
+    pub owner_user_id: Option<String>,
+}
+
+/// [`CreateWalletGroupRequest`] validate后；组名规则与wallet名相同
+#[derive(Debug, Clone)]
+pub struct CreateWalletGroup {
+    pub name: WalletNameParam,
+    pub owner_user_id: Option<String>,
+}
+
+impl Validate for CreateWalletGroup {
+    type Raw = CreateWalletGroupRequest;
+
+    fn validate(raw: CreateWalletGroupRequest) -> Result<Self, ParamError> {
+        Ok(Self {
+            name: WalletNameParam::try_from(raw.name.as_str())?,
+            owner_user_id: raw.owner_user_id.filter(|id| !id.trim().is_empty()),
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct WalletGroupResponse {
+    #[serde(flatten)]
+    pub group: crate::storage::WalletGroupRecord,
+    pub members: Vec<crate::storage::WalletGroupMember>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WalletGroupListResponse {
+    pub groups: Vec<crate::storage::WalletGroupRecord>,
+}
+
+/// 组内一个wallet的资产balance；address缺失等无法query时只有 `error`
+#[derive(Debug, Serialize)]
+pub struct GroupWalletBalances {
+    pub wallet: String,
+    pub balances: std::collections::HashMap<String, crate::api::handlers::multi_assets::AssetBalance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `GET /api/groups/:name/balances`
+#[derive(Debug, Serialize)]
+pub struct GroupBalancesResponse {
+    pub group: String,
+    pub wallets: Vec<GroupWalletBalances>,
+    /// 资产符号 → 所有成员balance之和
+    pub totals: std::collections::BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_currency: Option<String>,
+}
+
+/// `GET /api/groups/:name/history?cursor=&limit=`
+#[derive(Debug, Deserialize)]
+pub struct GroupHistoryQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+pub const MAX_GROUP_HISTORY_LIMIT: usize = 200;
+
+/// [`GroupHistoryQuery`] validate后
+#[derive(Debug, Clone)]
+pub struct GroupHistoryParams {
+    pub cursor: Option<crate::storage::TransactionCursor>,
+    pub limit: usize,
+}
+
+impl Validate for GroupHistoryParams {
+    type Raw = GroupHistoryQuery;
+
+    fn validate(raw: GroupHistoryQuery) -> Result<Self, ParamError> {
+        let cursor = raw
+            .cursor
+            .as_deref()
+            .filter(|c| !c.is_empty())
+            .map(|c| crate::storage::TransactionCursor::decode(c).map_err(|_| ParamError::Cursor))
+            .transpose()?;
+        Ok(Self { cursor, limit: raw.limit.unwrap_or(50).clamp(1, MAX_GROUP_HISTORY_LIMIT) })
+    }
+}
+
+/// 组历史中的一条本地transaction记录
+#[derive(Debug, Serialize)]
+pub struct GroupHistoryEntry {
+    pub wallet: String,
+    pub hash: String,
+    pub network: String,
+    pub status: String,
+    pub from: String,
+    pub to: String,
+    pub amount: String,
+    pub fee: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    pub links: ExplorerLinks,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupHistoryResponse {
+    pub group: String,
+    /// 所有成员的transaction按时间倒序合并
+    pub transactions: Vec<GroupHistoryEntry>,
+    /// 下一页游标；最后一页省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// `POST /api/groups/:name/sweep`
+#[derive(Deserialize)]
+pub struct GroupSweepRequest {
+    pub to: String,
+    pub network: String,
+    /// 默认只生成报告；显式传 `false` 才发送
+    #[serde(default = "default_true")]
+    pub dry_run: bool,
+    /// wallet名 → Password（托管wallet服务端sign）；没有Password的成员跳过
+    #[serde(default)]
+    pub passwords: std::collections::HashMap<String, String>,
+    /// 确认 `to` 是合约时仍然发送
+    #[serde(default)]
+    pub acknowledge_contract_recipient: bool,
+}
+
+/// [`GroupSweepRequest`] validate后（不实现 Debug，避免Password进日志）
+pub struct GroupSweep {
+    pub to: EvmAddress,
+    pub network: NetworkName,
+    pub dry_run: bool,
+    pub passwords: std::collections::HashMap<String, String>,
+    pub acknowledge_contract_recipient: bool,
+}
+
+impl Validate for GroupSweep {
+    type Raw = GroupSweepRequest;
+
+    fn validate(raw: GroupSweepRequest) -> Result<Self, ParamError> {
+        Ok(Self {
+            to: EvmAddress::try_from(raw.to.as_str())?,
+            network: NetworkName::try_from(raw.network.as_str())?.require_evm()?,
+            dry_run: raw.dry_run,
+            passwords: raw.passwords,
+            acknowledge_contract_recipient: raw.acknowledge_contract_recipient,
+        })
+    }
+}
+
+/// 成员wallet的清空结果：`would_send`（dry run）、`sent`、`pending_approval`、
+/// `skipped` 或 `failed`
+#[derive(Debug, Default, Serialize)]
+pub struct GroupSweepResult {
+    pub wallet: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// 原生币，十进制
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<String>,
+    /// balance - fee
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approval_id: Option<String>,
+    /// 跳过或失败的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupSweepResponse {
+    pub group: String,
+    pub network: String,
+    pub to: String,
+    pub dry_run: bool,
+    pub gas_price_wei: String,
+    /// 已发送 / 待审批 / dry run 中将发送的金额合计
+    pub total_amount: String,
+    pub results: Vec<GroupSweepResult>,
+}
+
+/// 交易与双方address的区块浏览器链接；network 未配置模板时各字段省略（不输出 null）
+#[derive(Debug, Default, Serialize)]
+pub struct ExplorerLinks {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_explorer_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_explorer_url: Option<String>,
+}
+
+impl ExplorerLinks {
+    pub fn render(
+        config: &crate::core::config::BlockchainConfig,
+        network: &str,
+        hash: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Self {
+        Self {
+            explorer_url: config.explorer_tx_url(network, hash),
+            from_explorer_url: from.and_then(|a| config.explorer_address_url(network, a)),
+            to_explorer_url: to.and_then(|a| config.explorer_address_url(network, a)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BridgeAssetsRequest {
+    pub from_wallet: String,
+    pub from_chain: String,
+    pub to_chain: String,
+    pub token: String,
+    pub amount: String,
+    /// 幂等键（可选，防止重复提交）
+    #[serde(skip_serializing_if = "Option::is_none", alias = "clientRequestId")]
+    pub client_request_id: Option<String>,
+}
+
+/// [`BridgeAssetsRequest`] validate后
+#[derive(Debug, Clone)]
+pub struct BridgeAssets {
+    pub from_wallet: WalletNameParam,
+    pub from_chain: NetworkName,
+    pub to_chain: NetworkName,
+    pub token: String,
+    pub amount: Amount,
+    pub client_request_id: Option<String>,
+}
+
+impl Validate for BridgeAssets {
+    type Raw = BridgeAssetsRequest;
+
+    fn validate(raw: BridgeAssetsRequest) -> Result<Self, ParamError> {
+        if raw.token.is_empty() {
+            return Err(ParamError::Missing("token"));
+        }
+        Ok(Self {
+            from_wallet: WalletNameParam::try_from(raw.from_wallet.as_str())?,
+            from_chain: NetworkName::try_from(raw.from_chain.as_str())?,
+            to_chain: NetworkName::try_from(raw.to_chain.as_str())?,
+            amount: Amount::try_from(raw.amount.as_str())?,
+            token: raw.token,
+            client_request_id: raw.client_request_id,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct BridgeResponse {
+    /// 桥接ID（前端期望）
+    pub bridge_id: String,
+    /// 桥接transactionID（兼容字段）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bridge_tx_id: Option<String>,
+    /// 状态（新增，前端需要）
+    pub status: String,
+    /// 目标链（前端期望）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_chain: Option<String>,
+    /// 金额（前端期望）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    /// 源链（可选）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_chain: Option<String>,
+    /// 代币（可选）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BridgeTransactionResponse {
+    pub id: String,
+    pub from_wallet: String,
+    pub from_chain: String,
+    pub to_chain: String,
+    pub token: String,
+    pub amount: String,
+    pub status: String,
+    pub source_tx_hash: Option<String>,
+    pub destination_tx_hash: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub fee_amount: Option<String>,
+    pub estimated_completion_time: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BalanceResponse {
+    pub balance: String,
+    pub network: String,
+    pub symbol: String,
+    /// 当前价格下的法币价值；定价关闭或价格不可用时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fiat_value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fiat_currency: Option<String>,
+    /// 以下仅 ERC-20 balance：合约address、最小单位的原始数量与 `decimals()`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_balance: Option<String>,
+    /// token 未实现 `decimals()` 时省略，`balance` 此时即原始数量
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+}
+
+/// `GET /api/wallets/:name/allowance?network=&token=&spender=`
+#[derive(Debug, Deserialize)]
+pub struct AllowanceQuery {
+    pub network: String,
+    pub token: String,
+    pub spender: String,
+}
+
+/// [`AllowanceQuery`] validate后；只支持 EVM network
+#[derive(Debug, Clone)]
+pub struct AllowanceParams {
+    pub network: NetworkName,
+    pub token: EvmAddress,
+    pub spender: EvmAddress,
+}
+
+impl Validate for AllowanceParams {
+    type Raw = AllowanceQuery;
+
+    fn validate(raw: AllowanceQuery) -> Result<Self, ParamError> {
+        Ok(Self {
+            network: NetworkName::try_from(raw.network.as_str())?.require_evm()?,
+            token: EvmAddress::try_from(raw.token.as_str())?,
+            spender: EvmAddress::try_from(raw.spender.as_str())?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AllowanceResponse {
+    pub network: String,
+    pub token: String,
+    /// wallet address（授权方）
+    pub owner: String,
+    pub spender: String,
+    /// 最小单位的授权额度；无限授权为 2^256 - 1
+    pub allowance: String,
+}
+
+/// `POST /api/wallets/:name/approve`
+#[derive(Debug, Deserialize)]
+pub struct ApproveTokenRequest {
+    pub network: String,
+    pub token: String,
+    pub spender: String,
+    /// 最小单位的整数；`0` 即撤销授权
+    pub amount: String,
+    #[serde(default)]
+    pub password: String,
+    /// 确认无限授权（2^256 - 1）：spender 之后可转走该 token 的全部余额
+    #[serde(default)]
+    pub allow_unlimited: bool,
+}
+
+/// [`ApproveTokenRequest`] validate后：spender 不能是零address
+#[derive(Debug, Clone)]
+pub struct ApproveToken {
+    pub network: NetworkName,
+    pub token: EvmAddress,
+    pub spender: EvmAddress,
+    pub amount: TokenUnits,
+    pub password: String,
+    pub allow_unlimited: bool,
+}
+
+impl Validate for ApproveToken {
+    type Raw = ApproveTokenRequest;
+
+    fn validate(raw: ApproveTokenRequest) -> Result<Self, ParamError> {
+        let spender = EvmAddress::try_from(raw.spender.as_str())?;
+        if spender.as_str()[2..].bytes().all(|b| b == b'0') {
+            return Err(ParamError::ZeroSpender);
+        }
+        if raw.password.is_empty() {
+            return Err(ParamError::Missing("password"));
+        }
+        Ok(Self {
+            network: NetworkName::try_from(raw.network.as_str())?.require_evm()?,
+            token: EvmAddress::try_from(raw.token.as_str())?,
+            spender,
+            amount: TokenUnits::try_from(raw.amount.as_str())?,
+            password: raw.password,
+            allow_unlimited: raw.allow_unlimited,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApproveTokenResponse {
+    pub tx_hash: String,
+    pub network: String,
+    pub token: String,
+    pub spender: String,
+    /// 实际授权的最小单位数量
+    pub amount: String,
+}
+
+/// `GET /api/wallets/:name/balance_history`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BalanceHistoryResponse {
+    pub wallet: String,
+    pub network: String,
+    pub token: String,
+    pub resolution: crate::storage::SnapshotResolution,
+    pub from: chrono::DateTime<chrono::Utc>,
+    pub to: chrono::DateTime<chrono::Utc>,
+    pub points: Vec<crate::storage::BalancePoint>,
+}
+
+/// `GET /api/analytics/fees`
+#[derive(Debug, Serialize)]
+pub struct FeeAnalyticsResponse {
+    pub network: String,
+    pub window_secs: i64,
+    pub group_by: crate::storage::FeeGrouping,
+    pub totals: crate::storage::FeeSummary,
+    pub buckets: Vec<crate::storage::FeeSummary>,
+}
+
+/// 不带分页参数时的wallet历史（链上结果 + 本地记录，一次返回）
+#[derive(Serialize)]
+pub struct TransactionHistoryResponse {
+    pub transactions: Vec<HistoryTransaction>,
+    /// `fiat_value` 的计价货币；定价关闭时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_currency: Option<String>,
+}
+
+/// 带 `cursor`/`limit` 时的wallet历史
+#[derive(Serialize)]
+pub struct HistoryPageResponse {
+    #[serde(flatten)]
+    pub page: Page<HistoryTransaction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_currency: Option<String>,
+}
+
+/// 历史条目：链上查询结果没有 network；本地记录的发送带 network、状态与浏览器链接
+#[derive(Serialize)]
+pub struct HistoryTransaction {
+    #[serde(flatten)]
+    pub transaction: crate::blockchain::traits::TransactionInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(flatten)]
+    pub links: ExplorerLinks,
+    /// 按当前价格折算；定价关闭或价格不可用时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_value: Option<String>,
+    /// 按交易时间点的价格快照折算；只有本地记录且当时有快照时才有
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_value_at_time: Option<String>,
+    /// 按收据计算的实际手续费（gas used × effective gas price）；确认前省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_actual: Option<String>,
+    /// 打包所在区块；确认前省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncryptedBackupResponse {
+    /// Format version for the encrypted backup object
+    pub version: String,
+    /// Algorithm used, e.g. AES-256-GCM
+    pub alg: String,
+    /// KEK identifier used to encrypt this backup (optional)
+    pub kek_id: Option<String>,
+    /// Base64-encoded nonce
+    pub nonce: String,
+    /// Base64-encoded ciphertext (encrypted seed phrase)
+    pub ciphertext: String,
+    /// Wallet name for reference
+    pub wallet: String,
+    /// Wallet description, carried in the clear so a restore can reapply it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Wallet metadata, carried in the clear alongside the description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+    /// The wallet was created with a BIP39 passphrase that is not part of the
+    /// backup; restoring needs the seed phrase *and* that passphrase
+    #[serde(default)]
+    pub passphrase_required: bool,
+    /// Human-readable recovery notice, set when `passphrase_required`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+// Backwards-compatible alias for handler usage in tests; production handlers should
+// return `EncryptedBackupResponse`. For test-env, we still allow returning plaintext
+// in the `ciphertext` field with `alg = "PLAINTEXT"` to preserve deterministic tests.
+pub type BackupResponse = EncryptedBackupResponse;
+
+#[derive(Clone, Deserialize)]
+pub struct RestoreWalletRequest {
+    pub name: String,
+    pub seed_phrase: String,
+    #[serde(default)]
+    pub quantum_safe: bool,
+    /// Wallet password（可选）：给出时派生的主密钥按它加密保存，恢复后可直接sign；
+    /// 使用 `passphrase` 时必须提供
+    #[serde(default)]
+    pub password: Option<String>,
+    /// BIP39 passphrase（可选）：创建时用过就必须提供同一个；输错不会报错，
+    /// 而是恢复出另一组address，响应里的 `address` 用于核对
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+    /// 指定network（可选："eth" | "btc" | "bsc" | "polygon"）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    /// 自定义派生路径（可选，如 m/44'/60'/0'/0）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+    /// address类型（可选："btc_bech32" | "btc_p2sh" | "btc_legacy" | "evm"）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_type: Option<String>,
+    /// 起始索引（可选，默认0）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_index: Option<u32>,
+    /// 批量导入数量（可选，默认1）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub import_count: Option<u32>,
+    /// 备份中的wallet描述（可选，恢复后写回）
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 备份中的元数据（可选，恢复后写回）
+    #[serde(default)]
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+// mnemonic、passphrase 与Password都不进日志
+impl std::fmt::Debug for RestoreWalletRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RestoreWalletRequest")
+            .field("name", &self.name)
+            .field("quantum_safe", &self.quantum_safe)
+            .field("network", &self.network)
+            .field("derivation_path", &self.derivation_path)
+            .finish_non_exhaustive()
+    }
+}
+
+/// `POST /api/wallets/import_keystore` 请求（Password字段在drop时清零）
+#[derive(Deserialize, zeroize::Zeroize, zeroize::ZeroizeOnDrop)]
+pub struct ImportKeystoreRequest {
+    pub name: String,
+    /// keystore V3 JSON（对象或JSON字符串）
+    #[zeroize(skip)]
+    pub keystore: serde_json::Value,
+    /// keystore 文件Password，仅用于解密
+    pub keystore_password: String,
+    /// 导入后wallet使用的Password
+    pub wallet_password: String,
+}
+
+/// `POST /api/wallets/:name/export_keystore` 请求（Password字段在drop时清零）
+#[derive(Deserialize, zeroize::Zeroize, zeroize::ZeroizeOnDrop)]
+pub struct ExportKeystoreRequest {
+    pub wallet_password: String,
+    /// 导出 keystore 的Password
+    pub export_password: String,
+}
+
+/// `POST /api/wallets/:name/export` 请求（Password字段在drop时清零）
+#[derive(Deserialize, zeroize::Zeroize, zeroize::ZeroizeOnDrop)]
+pub struct ExportWalletRequest {
+    /// wallet Password，同时用于加密导出的 keystore
+    pub password: String,
+}
+
+/// `POST /api/wallets/import` 请求（Password字段在drop时清零）
+#[derive(Deserialize, zeroize::Zeroize, zeroize::ZeroizeOnDrop)]
+pub struct ImportWalletRequest {
+    pub name: String,
+    /// `POST /api/wallets/:name/export` 导出的 keystore（对象或JSON字符串）
+    #[zeroize(skip)]
+    pub keystore: serde_json::Value,
+    /// 导出时的wallet Password，导入后沿用
+    pub password: String,
+}
+
+/// `POST /api/wallets/:name/backup/shamir` 请求（Password字段在drop时清零）
+#[derive(Deserialize, zeroize::Zeroize, zeroize::ZeroizeOnDrop)]
+pub struct ShamirBackupRequest {
+    pub password: String,
+    /// 恢复所需份数（2..=total）
+    pub threshold: u8,
+    /// 总份数（至多 16）
+    pub total: u8,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShamirBackupResponse {
+    pub wallet: String,
+    pub backup_id: String,
+    pub threshold: u8,
+    pub total: u8,
+    pub address: String,
+    /// 每份应分开保管；任意 `threshold` 份即可恢复
+    pub shares: Vec<ShareEnvelope>,
+}
+
+/// `POST /api/wallets/restore/shamir` 请求（Password字段在drop时清零）
+#[derive(Deserialize, zeroize::Zeroize, zeroize::ZeroizeOnDrop)]
+pub struct ShamirRestoreRequest {
+    pub name: String,
+    #[zeroize(skip)]
+    pub shares: Vec<ShareEnvelope>,
+    /// 恢复后wallet使用的Password
+    pub password: String,
+}
+
+/// `POST /api/wallets/import_enveloped_keystore` 请求（Password字段在drop时清零）
+#[derive(Deserialize, zeroize::Zeroize, zeroize::ZeroizeOnDrop)]
+pub struct ImportEnvelopedKeystoreRequest {
+    pub name: String,
+    /// 带签名的 keystore 信封（对象或JSON字符串）
+    #[zeroize(skip)]
+    pub envelope: serde_json::Value,
+    /// 内层 keystore 的Password
+    pub keystore_password: String,
+    pub wallet_password: String,
+    /// 对方传输公钥（SEC1 hex，压缩或非压缩），签名者不一致时拒绝
+    pub expected_sender: String,
+}
+
+/// `POST /api/wallets/:name/export_enveloped_keystore` 请求（Password字段在drop时清零）
+#[derive(Deserialize, zeroize::Zeroize, zeroize::ZeroizeOnDrop)]
+pub struct ExportEnvelopedKeystoreRequest {
+    pub wallet_password: String,
+    pub export_password: String,
+    /// 原样写入信封 meta.label
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MultiSigTransactionRequest {
+    /// 目标address（优先使用 to，兼容 to_address）
+    #[serde(alias = "to_address")]
+    pub to: String,
+    pub amount: String,
+    pub network: String,
+    pub signatures: Vec<String>,
+}
+
+/// [`MultiSigTransactionRequest`] validate后
+#[derive(Debug, Clone)]
+pub struct MultiSigTransaction {
+    pub to: EvmAddress,
+    pub amount: Amount,
+    pub network: NetworkName,
+    pub signatures: Vec<String>,
+}
+
+impl Validate for MultiSigTransaction {
+    type Raw = MultiSigTransactionRequest;
+
+    fn validate(raw: MultiSigTransactionRequest) -> Result<Self, ParamError> {
+        Ok(Self {
+            to: EvmAddress::try_from(raw.to.as_str())?,
+            amount: Amount::try_from(raw.amount.as_str())?,
+            network: NetworkName::try_from(raw.network.as_str())?.require_evm()?,
+            signatures: raw.signatures,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultiSigTransactionResponse {
+    pub tx_hash: String,
+    pub status: String,
+}
+
+/// 结构化 API error：`{"error": {"code", "message", "request_id"}}`，可带 `details`
+///
+/// 状态码默认取错误目录里 `code` 的声明。5xx 的消息一律经过
+/// [`sanitize_error_message`]；由 [`WalletError`] / [`anyhow::Error`] 转换来的
+/// 5xx 只返回目录里的通用描述，原始错误只写日志。
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: ApiErrorCode,
+    message: String,
+    details: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiErrorBody {
+    pub error: ApiErrorDetail,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiErrorDetail {
+    pub code: String,
+    pub message: String,
+    /// 与响应头 `X-Request-Id` 相同
+    pub request_id: Option<String>,
+    /// 该错误码附带的结构化信息，例如 `ANOMALY_BLOCKED` 的分数与等级
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    pub fn new(code: ApiErrorCode, message: impl Into<String>) -> Self {
+        let status = StatusCode::from_u16(code.entry().status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        Self { status, code, message: message.into(), details: None }
+    }
+
+    pub fn with_details(self, details: serde_json::Value) -> Self {
+        Self { details: Some(details), ..self }
+    }
+
+    /// 领域层错误以字符串携带 code；不在错误目录里的记为 `INTERNAL_ERROR` 并告警
+    pub fn from_code(status: StatusCode, code: &str, message: impl Into<String>) -> Self {
+        let code = ApiErrorCode::from_code(code).unwrap_or_else(|| {
+            tracing::warn!(code, "error code missing from the error catalog");
+            ApiErrorCode::InternalError
+        });
+        Self { status, code, message: message.into(), details: None }
+    }
+
+    /// 消息使用错误目录里的描述，用于不应透露细节的内部错误
+    pub fn generic(code: ApiErrorCode) -> Self {
+        Self::new(code, code.entry().description)
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn code(&self) -> ApiErrorCode {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn from_wallet_error(e: &WalletError) -> Self {
+        let code = match e {
+            WalletError::NotFoundError(_) => ApiErrorCode::WalletNotFound,
+            WalletError::ValidationError(_) | WalletError::InvalidInput(_) => ApiErrorCode::InvalidInput,
+            WalletError::InvalidAddress(_) | WalletError::AddressError(_) => ApiErrorCode::InvalidAddress,
+            WalletError::InvalidAmount(_) => ApiErrorCode::InvalidAmount,
+            WalletError::MnemonicError(_) => ApiErrorCode::InvalidMnemonic,
+            WalletError::InvalidPassword(_) => ApiErrorCode::InvalidPassword,
+            WalletError::SecurityError(_) => ApiErrorCode::WalletAccessDenied,
+            WalletError::NetworkNotAllowed(_) => ApiErrorCode::NetworkNotAllowed,
+            WalletError::UnsupportedWalletKind(_) => ApiErrorCode::UnsupportedWalletKind,
+            WalletError::KeyRotationRequired(_) => ApiErrorCode::KeyRotationRequired,
+            WalletError::InsufficientFunds(_) => ApiErrorCode::InsufficientFunds,
+            WalletError::NetworkBusy(_) => ApiErrorCode::NetworkBusy,
+            WalletError::NetworkError(_) => ApiErrorCode::NetworkError,
+            WalletError::BlockchainError(_) => ApiErrorCode::RpcError,
+            WalletError::BridgeError(_) => ApiErrorCode::BridgeFailed,
+            WalletError::TransactionFailed(_) => ApiErrorCode::TransactionFailed,
+            WalletError::StorageError(_) => ApiErrorCode::DbError,
+            WalletError::SigningFailed(_) => ApiErrorCode::SigningFailed,
+            WalletError::EncryptionError(_) => ApiErrorCode::EncryptionFailed,
+            _ => ApiErrorCode::InternalError,
+        };
+        let error = Self::generic(code);
+        if error.status.is_server_error() {
+            tracing::error!(code = %code, "request failed: {}", sanitize_for_logging(&e.to_string()));
+            note_wallet_error(e);
+            return error;
+        }
+        Self { message: sanitize_error_message(&e.to_string()), ..error }
+    }
+}
+
+impl From<WalletError> for ApiError {
+    fn from(e: WalletError) -> Self {
+        Self::from_wallet_error(&e)
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        if let Some(wallet_error) = e.downcast_ref::<WalletError>() {
+            return Self::from_wallet_error(wallet_error);
+        }
+        tracing::error!("request failed: {}", sanitize_for_logging(&format!("{:#}", e)));
+        note_error(&e, ErrorClass::Internal);
+        Self::generic(ApiErrorCode::InternalError)
+    }
+}
+
+impl From<ParamError> for ApiError {
+    fn from(e: ParamError) -> Self {
+        let code = ApiErrorCode::from_code(e.code()).unwrap_or(ApiErrorCode::InvalidRequest);
+        Self { status: e.status(), code, message: e.to_string(), details: None }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let message =
+            if self.status.is_server_error() { sanitize_error_message(&self.message) } else { self.message };
+        let body = ApiErrorBody {
+            error: ApiErrorDetail {
+                code: self.code.as_str().to_string(),
+                message,
+                request_id: current_request_id(),
+                details: self.details,
+            },
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RotateSigningKeyResponse {
+    pub wallet: String,
+    pub old_version: u32,
+    pub new_version: u32,
+}
+
+/// 跨wallet transaction 查询结果中的单条记录（不含wallet密文）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminTransactionRecord {
+    pub id: String,
+    pub wallet_id: String,
+    pub tx_hash: String,
+    pub network: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub amount: String,
+    pub fee: String,
+    pub status: String,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmed_at: Option<String>,
+}
+
+impl From<crate::storage::TransactionRecord> for AdminTransactionRecord {
+    fn from(tx: crate::storage::TransactionRecord) -> Self {
+        Self {
+            id: tx.id,
+            wallet_id: tx.wallet_id,
+            tx_hash: tx.tx_hash,
+            network: tx.network,
+            from_address: tx.from_address,
+            to_address: tx.to_address,
+            amount: tx.amount,
+            fee: tx.fee,
+            status: tx.status,
+            created_at: tx.created_at.to_rfc3339(),
+            confirmed_at: tx.confirmed_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdminTransactionsResponse {
+    pub transactions: Vec<AdminTransactionRecord>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BroadcastRawTransactionResponse {
+    pub tx_hash: String,
+    pub transaction: crate::blockchain::ethereum::raw_tx::DecodedRawTransaction,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecheckTransactionsResponse {
+    /// 本批次实际查询的transaction数
+    pub checked: usize,
+    /// 状态发生变化的transaction数
+    pub transitioned: usize,
+    /// RPC 查询failed的transaction数
+    pub errors: usize,
+}
+
+/// `GET /api/admin/summary` 中的一个租约
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LockStatus {
+    pub name: String,
+    pub holder_id: String,
+    /// Unix 毫秒
+    pub expires_at: i64,
+    pub acquired_at: i64,
+    /// 是否由本实例持有
+    pub held_by_self: bool,
+}
+
+/// `GET /api/admin/summary`：本实例身份与当前有效的分布式锁
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdminSummaryResponse {
+    /// 启动时生成的实例 UUID（即锁的 holder_id）
+    pub instance_id: String,
+    pub multi_instance: bool,
+    pub locks: Vec<LockStatus>,
+    /// 本实例的后台任务状态
+    pub jobs: Vec<crate::ops::jobs::JobState>,
+    /// 本实例wallet元数据缓存的命中与失效计数
+    pub wallet_cache: crate::storage::WalletCacheStats,
+    /// 与 `GET /api/version` 相同的构建与运行时信息
+    pub build: crate::build_info::BuildInfo,
+    pub runtime: crate::build_info::RuntimeInfo,
+}
+
+/// `GET /api/version`：构建信息（编译期确定）与本进程的运行时信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionResponse {
+    pub build: crate::build_info::BuildInfo,
+    pub runtime: crate::build_info::RuntimeInfo,
+}
+
+/// `GET /api/admin/jobs`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobsResponse {
+    pub jobs: Vec<crate::ops::jobs::JobState>,
+    /// 所有任务都健康
+    pub healthy: bool,
+}
+
+/// `GET /api/admin/incidents`：窗口内的 panic 记录与本实例的错误分类排行
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IncidentsResponse {
+    pub window_secs: u64,
+    /// 最近的 panic，新的在前，最多 `limit` 条
+    pub panics: Vec<crate::storage::IncidentRecord>,
+    /// 窗口内 panic 总数（不受 `limit` 限制）
+    pub panic_count: i64,
+    /// 本实例窗口内失败请求按错误分类计数，多的在前
+    pub error_classes: Vec<crate::ops::incidents::ErrorClassCount>,
+}
+
+/// `POST /api/wallets/:name/backfill` 的查询参数
+#[derive(Debug, Deserialize)]
+pub struct BackfillQuery {
+    pub network: String,
+    /// 起始区块，默认从创世块开始
+    pub from_block: Option<u64>,
+}
+
+/// [`BackfillQuery`] validate后
+pub struct BackfillParams {
+    pub network: NetworkName,
+    pub from_block: Option<u64>,
+}
+
+impl Validate for BackfillParams {
+    type Raw = BackfillQuery;
+
+    fn validate(raw: BackfillQuery) -> Result<Self, ParamError> {
+        Ok(Self { network: NetworkName::try_from(raw.network.as_str())?, from_block: raw.from_block })
+    }
+}
+
+/// `POST /api/wallets/:name/backfill`
+#[derive(Debug, Serialize)]
+pub struct BackfillResponse {
+    pub cursor: crate::storage::ScanCursor,
+    /// 后台回填任务已登记并被唤起；为 false 时（`backfill.enabled = false`）需用 CLI 执行
+    pub scheduled: bool,
+}
+
+/// 一个network上已回填账目的按币种汇总（最小单位）
+#[derive(Debug, Serialize)]
+pub struct NetworkLedgerBalances {
+    pub network: String,
+    pub balances: Vec<crate::storage::LedgerBalance>,
+}
+
+/// `GET /api/wallets/:name/backfill/status`
+#[derive(Debug, Serialize)]
+pub struct BackfillStatusResponse {
+    pub wallet_name: String,
+    pub cursors: Vec<crate::storage::ScanCursor>,
+    pub ledger: Vec<NetworkLedgerBalances>,
+}
+
+/// `POST /api/admin/rotate_api_key` 响应；新的第二把密钥只在这里出现一次。
+/// 不实现 `Debug`，避免被日志打印
+#[derive(Serialize)]
+pub struct ApiKeyRotationResponse {
+    pub secondary_api_key: String,
+    /// 两把密钥的 SHA-256 指纹（前 8 字节），与审计日志中的一致
+    pub primary_fingerprint: String,
+    pub secondary_fingerprint: String,
+}
+
+/// `GET /api/admin/flags?wallet_id=` 的查询参数
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FlagsQuery {
+    /// 给出时 `enabled` 是对该wallet的判定，否则是不针对wallet的判定
+    pub wallet_id: Option<String>,
+}
+
+/// `GET /api/admin/flags`：代码中声明的全部开关
+#[derive(Debug, Serialize)]
+pub struct FlagsResponse {
+    pub wallet_id: Option<String>,
+    pub flags: Vec<crate::feature_flags::FlagState>,
+}
+
+/// `PUT /api/admin/flags` 请求体
+#[derive(Debug, Deserialize)]
+pub struct FlagUpdateRequest {
+    pub flag: String,
+    /// 省略时修改全局规则
+    #[serde(default)]
+    pub wallet_id: Option<String>,
+    /// `null` 或省略时删除该范围的规则，回落到全局规则或默认值
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// 0-100，只用于全局规则：按wallet id 哈希分桶，只对这部分wallet开启
+    #[serde(default)]
+    pub rollout_percent: Option<u8>,
+    /// 记入规则与审计日志的操作人，默认 `admin`
+    #[serde(default)]
+    pub updated_by: Option<String>,
+}
+
+/// `GET /api/admin/tokens`
+#[derive(Debug, Serialize)]
+pub struct TokensResponse {
+    /// 配置或数据库中设置过行为的 token，按网络与address排序
+    pub tokens: Vec<crate::token_registry::TokenEntry>,
+}
+
+/// `PUT /api/admin/tokens/:network/:address` 请求体；省略的标志为 `false`
+#[derive(Debug, Deserialize)]
+pub struct TokenBehaviorRequest {
+    #[serde(flatten)]
+    pub behavior: crate::blockchain::erc20::TokenBehavior,
+    /// 记入设置与审计日志的操作人，默认 `admin`
+    #[serde(default)]
+    pub updated_by: Option<String>,
+}
+
+/// `POST /api/wallets/:name/timelocks`（不实现 Debug，避免Password进日志）
+#[derive(Deserialize)]
+pub struct CreateTimelockRequest {
+    pub network: String,
+    pub to: String,
+    /// 最小单位（wei）十进制整数
+    pub value: String,
+    /// 合约调用的 calldata（0x 十六进制）；省略为普通转账
+    #[serde(default)]
+    pub data: Option<String>,
+    /// 解锁时间（Unix 秒）；与 `unlock_block` 至少给出一个，都给出时都要满足
+    #[serde(default)]
+    pub unlock_at: Option<i64>,
+    #[serde(default)]
+    pub unlock_block: Option<u64>,
+    /// 过期时间（Unix 秒）；默认解锁时间加 `timelocks.default_release_window_secs`
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+    /// 收款方是合约时需要确认，与普通发送相同
+    #[serde(default)]
+    pub acknowledge_contract_recipient: bool,
+    pub password: String,
+}
+
+/// `POST /api/timelocks/:id/release`（不实现 Debug，避免份额进日志）
+#[derive(Deserialize)]
+pub struct ReleaseTimelockRequest {
+    /// 托管方在解锁后交回的份额（十六进制）
+    pub custodian_share: String,
+}
+
+/// `GET /api/wallets/:name/timelocks`
+#[derive(Debug, Serialize)]
+pub struct TimelockListResponse {
+    /// 新的在前，含已释放、已过期和已作废的
+    pub timelocks: Vec<crate::storage::TimelockRecord>,
+}
+
+/// `GET /api/admin/backups`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupHistoryResponse {
+    /// 是否启用定时备份
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// 最近的备份记录，新的在前
+    pub backups: Vec<crate::storage::BackupRecord>,
+}
+
+/// `POST /api/admin/backups/run`
+pub type BackupRunResponse = crate::ops::db_backup::BackupRunReport;
+
+/// `POST /api/admin/db/checkpoint`
+pub type DbCheckpointResponse = crate::ops::wal::CheckpointReport;
+
+/// `POST /api/admin/db/vacuum-into` 请求体
+#[derive(Debug, Deserialize)]
+pub struct VacuumIntoRequest {
+    /// 写入备份 sink 的对象名（单一路径分量）
+    pub object_key: String,
+}
+
+/// `POST /api/admin/db/vacuum-into`
+pub type VacuumIntoResponse = crate::ops::db_backup::VacuumIntoReport;
+
+/// `POST /api/admin/sandbox-clone` 请求体（仅非生产构建）
+#[derive(Debug, Deserialize)]
+pub struct SandboxCloneRequest {
+    /// 源数据库 URL（`sqlite://...`），只读打开
+    pub source_db: String,
+    /// 要克隆的wallet；缺省克隆全部
+    #[serde(default)]
+    pub wallets: Option<Vec<String>>,
+    /// 新建 sandbox wallet的Password
+    pub password: String,
+}
+
+/// `POST /api/admin/sandbox-clone`
+pub type SandboxCloneResponse = crate::ops::sandbox_clone::CloneReport;
+
+/// `GET /api/events?after_seq=`（已废弃的旧参数）的响应；使用 `cursor` 时返回 [`Page`]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventsResponse {
+    /// 按 `seq` 升序
+    pub events: Vec<crate::storage::JournalEvent>,
+    /// 下一次请求的 `after_seq`；没有新事件时等于本次的 `after_seq`
+    pub next_seq: i64,
+}
+
+/// `POST /api/relay/meta_tx`
+#[derive(Debug, Deserialize)]
+pub struct MetaTxRelayRequest {
+    pub request: crate::relay::ForwardRequest,
+    /// 65 字节 EIP-712 sign（0x hex）
+    pub signature: String,
+}
+
+/// `POST /api/relay/meta_tx`
+pub type MetaTxRelayResponse = crate::relay::RelayReceipt;
+
+/// `GET /api/relay/meta_tx?from=0x...`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetaTxRelayListResponse {
+    pub relays: Vec<crate::storage::MetaTxRelayRecord>,
+}
+
+/// `POST /api/wallets/:name/tokens`
+#[derive(Debug, Deserialize)]
+pub struct CreateWalletTokenRequest {
+    /// `read_history` / `read_balance` / `send`
+    pub capabilities: Vec<String>,
+    /// 单笔发送上限（整单位十进制）；省略表示不设上限
+    pub amount_cap: Option<String>,
+    /// 默认 30 天
+    pub expires_in_secs: Option<u64>,
+    /// 仅 admin（API key）签发时需要：wallet所属user
+    pub owner_user_id: Option<String>,
+    /// `bearer`（默认）或 `hmac`（请求签名，见 `middleware::request_signing`）
+    pub auth_mode: Option<String>,
+}
+
+/// token 有效期上限（一年）
+pub const MAX_WALLET_TOKEN_LIFETIME_SECS: u64 = 365 * 24 * 3600;
+const DEFAULT_WALLET_TOKEN_LIFETIME_SECS: u64 = 30 * 24 * 3600;
+
+/// [`CreateWalletTokenRequest`] validate后
+#[derive(Debug, Clone)]
+pub struct CreateWalletToken {
+    /// [`crate::storage::WalletCapability`] 位集
+    pub capabilities: i64,
+    pub amount_cap: Option<Amount>,
+    pub expires_in_secs: u64,
+    pub owner_user_id: Option<String>,
+    /// [`crate::storage::AUTH_MODE_BEARER`] 或 [`crate::storage::AUTH_MODE_HMAC`]
+    pub auth_mode: &'static str,
+}
+
+impl Validate for CreateWalletToken {
+    type Raw = CreateWalletTokenRequest;
+
+    fn validate(raw: CreateWalletTokenRequest) -> Result<Self, ParamError> {
+        if raw.capabilities.is_empty() {
+            return Err(ParamError::Missing("capabilities"));
+        }
+        let mut capabilities = 0;
+        for name in &raw.capabilities {
+            let capability = crate::storage::WalletCapability::from_name(name)
+                .ok_or(ParamError::CapabilityUnknown)?;
+            capabilities |= capability.bit();
+        }
+        let amount_cap = raw.amount_cap.as_deref().map(Amount::try_from).transpose()?;
+        // 上限之后按 wei 比较，必须能表示为 U256
+        if amount_cap.as_ref().is_some_and(|cap| ethers::utils::parse_ether(cap.as_str()).is_err()) {
+            return Err(ParamError::AmountTooLong);
+        }
+        let expires_in_secs = raw.expires_in_secs.unwrap_or(DEFAULT_WALLET_TOKEN_LIFETIME_SECS);
+        if expires_in_secs == 0 || expires_in_secs > MAX_WALLET_TOKEN_LIFETIME_SECS {
+            return Err(ParamError::TokenLifetime(MAX_WALLET_TOKEN_LIFETIME_SECS));
+        }
+        let auth_mode = match raw.auth_mode.as_deref() {
+            None | Some(crate::storage::AUTH_MODE_BEARER) => crate::storage::AUTH_MODE_BEARER,
+            Some(crate::storage::AUTH_MODE_HMAC) => crate::storage::AUTH_MODE_HMAC,
+            Some(_) => return Err(ParamError::AuthMode),
+        };
+        Ok(Self { capabilities, amount_cap, expires_in_secs, owner_user_id: raw.owner_user_id, auth_mode })
+    }
+}
+
+/// `POST /api/wallets/:name/tokens`：明文 token 或签名 secret 只在此返回一次
+#[derive(Debug, Serialize)]
+pub struct WalletTokenCreatedResponse {
+    /// bearer token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// hmac 模式的签名 secret；请求只带 `X-Key-Id`（即 `id`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+    #[serde(flatten)]
+    pub record: crate::storage::WalletTokenRecord,
+}
+
+/// `GET /api/wallets/:name/tokens`
+#[derive(Debug, Serialize)]
+pub struct WalletTokenListResponse {
+    pub tokens: Vec<crate::storage::WalletTokenRecord>,
+}
+
+/// `POST /api/wallets/:name/subscriptions`
+#[derive(Debug, Deserialize)]
+pub struct CreateBalanceSubscriptionRequest {
+    pub network: String,
+    /// `native`（默认）或 ERC-20 合约address
+    pub token: Option<String>,
+    /// 触发通知的最小变化量（最小单位，十进制整数）；`0` 表示任何变化
+    pub min_delta: String,
+    /// 接收 `balance.changed` 事件的 webhook 目标标识
+    pub webhook_target: String,
+    /// 仅 admin（API key）创建时需要：wallet所属user
+    pub owner_user_id: Option<String>,
+}
+
+/// webhook 目标标识长度上限
+pub const MAX_WEBHOOK_TARGET_LEN: usize = 128;
+
+fn parse_min_delta(raw: &str) -> Result<u128, ParamError> {
+    if raw.is_empty() || !raw.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParamError::MinDelta);
+    }
+    raw.parse().map_err(|_| ParamError::MinDelta)
+}
+
+/// [`CreateBalanceSubscriptionRequest`] validate后
+#[derive(Debug, Clone)]
+pub struct CreateBalanceSubscription {
+    pub network: NetworkName,
+    /// [`crate::storage::NATIVE_TOKEN`] 或小写合约address
+    pub token: String,
+    pub min_delta: u128,
+    pub webhook_target: String,
+    pub owner_user_id: Option<String>,
+}
+
+impl Validate for CreateBalanceSubscription {
+    type Raw = CreateBalanceSubscriptionRequest;
+
+    fn validate(raw: CreateBalanceSubscriptionRequest) -> Result<Self, ParamError> {
+        let network = NetworkName::try_from(raw.network.as_str())?;
+        let token = match raw.token.as_deref() {
+            None | Some(crate::storage::NATIVE_TOKEN) => crate::storage::NATIVE_TOKEN.to_string(),
+            Some(contract) => {
+                // 代币只存在于 EVM network
+                network.require_evm()?;
+                EvmAddress::try_from(contract)?;
+                contract.to_lowercase()
+            }
+        };
+        let min_delta = parse_min_delta(&raw.min_delta)?;
+        let webhook_target = raw.webhook_target.trim();
+        if webhook_target.is_empty() || webhook_target.len() > MAX_WEBHOOK_TARGET_LEN {
+            return Err(ParamError::WebhookTarget(MAX_WEBHOOK_TARGET_LEN));
+        }
+        Ok(Self {
+            network,
+            token,
+            min_delta,
+            webhook_target: webhook_target.to_string(),
+            owner_user_id: raw.owner_user_id,
+        })
+    }
+}
+
+/// `PATCH /api/wallets/:name/subscriptions/:id`
+#[derive(Debug, Deserialize)]
+pub struct UpdateBalanceSubscriptionRequest {
+    pub active: Option<bool>,
+    pub min_delta: Option<String>,
+}
+
+/// [`UpdateBalanceSubscriptionRequest`] validate后
+#[derive(Debug, Clone, Copy)]
+pub struct UpdateBalanceSubscription {
+    pub active: Option<bool>,
+    pub min_delta: Option<u128>,
+}
+
+impl Validate for UpdateBalanceSubscription {
+    type Raw = UpdateBalanceSubscriptionRequest;
+
+    fn validate(raw: UpdateBalanceSubscriptionRequest) -> Result<Self, ParamError> {
+        let min_delta = raw.min_delta.as_deref().map(parse_min_delta).transpose()?;
+        Ok(Self { active: raw.active, min_delta })
+    }
+}
+
+/// `GET /api/wallets/:name/subscriptions`
+#[derive(Debug, Serialize)]
+pub struct BalanceSubscriptionListResponse {
+    pub subscriptions: Vec<crate::storage::BalanceSubscriptionRecord>,
+}
+
+/// `PUT /api/wallets/:name/multisig/policy`
+#[derive(Debug, Deserialize)]
+pub struct MultisigPolicyRequest {
+    pub network: String,
+    /// 压缩 secp256k1 公钥（hex）
+    pub signers: Vec<String>,
+    /// 按 `max_amount`（最小单位）升序
+    pub tiers: Vec<crate::crypto::multisig::ThresholdTier>,
+}
+
+/// [`MultisigPolicyRequest`] validate后
+#[derive(Debug, Clone)]
+pub struct MultisigPolicyUpdate {
+    pub network: NetworkName,
+    /// 规范化后的公钥 hex，与 `policy.signer_count()` 一一对应
+    pub signers: Vec<String>,
+    pub policy: crate::crypto::multisig::TieredThresholdPolicy,
+}
+
+impl Validate for MultisigPolicyUpdate {
+    type Raw = MultisigPolicyRequest;
+
+    fn validate(raw: MultisigPolicyRequest) -> Result<Self, ParamError> {
+        let network = NetworkName::try_from(raw.network.as_str())?.require_evm()?;
+        let mut signers = Vec::with_capacity(raw.signers.len());
+        for signer in &raw.signers {
+            let key = signer
+                .parse::<secp256k1::PublicKey>()
+                .map_err(|_| ParamError::MultisigPolicy(format!("invalid signer public key: {}", signer)))?;
+            let key = hex::encode(key.serialize());
+            if signers.contains(&key) {
+                return Err(ParamError::MultisigPolicy(format!("duplicate signer: {}", signer)));
+            }
+            signers.push(key);
+        }
+        let signer_count = u8::try_from(signers.len())
+            .map_err(|_| ParamError::MultisigPolicy("too many signers".to_string()))?;
+        let policy = crate::crypto::multisig::TieredThresholdPolicy::new(raw.tiers, signer_count)
+            .map_err(|e| ParamError::MultisigPolicy(e.to_string()))?;
+        Ok(Self { network, signers, policy })
+    }
+}
+
+/// `POST /api/wallets/:name/multisig/proposals`
+#[derive(Debug, Deserialize)]
+pub struct MultisigProposalRequest {
+    #[serde(alias = "to_address")]
+    pub to: String,
+    /// 整单位十进制金额；按策略档位比较前换算为最小单位
+    pub amount: String,
+    pub network: String,
+}
+
+/// [`MultisigProposalRequest`] validate后
+#[derive(Debug, Clone)]
+pub struct MultisigProposal {
+    pub to: EvmAddress,
+    pub amount: Amount,
+    pub network: NetworkName,
+}
+
+impl Validate for MultisigProposal {
+    type Raw = MultisigProposalRequest;
+
+    fn validate(raw: MultisigProposalRequest) -> Result<Self, ParamError> {
+        Ok(Self {
+            to: EvmAddress::try_from(raw.to.as_str())?,
+            amount: Amount::try_from(raw.amount.as_str())?,
+            network: NetworkName::try_from(raw.network.as_str())?.require_evm()?,
+        })
+    }
+}
+
+/// 多签提案详情：`required_signatures` 与 `policy` 是创建时冻结的值
+#[derive(Debug, Serialize)]
+pub struct MultisigProposalResponse {
+    pub id: String,
+    pub wallet: String,
+    pub to: String,
+    /// 最小单位（wei）
+    pub amount: String,
+    pub network: String,
+    pub required_signatures: u8,
+    pub signature_count: usize,
+    pub signers: Vec<String>,
+    pub policy: Option<crate::crypto::multisig::TieredThresholdPolicy>,
+    pub created_at: String,
+}
+
+impl From<&crate::crypto::multisig::MultiSigTransaction> for MultisigProposalResponse {
+    fn from(tx: &crate::crypto::multisig::MultiSigTransaction) -> Self {
+        Self {
+            id: tx.id.clone(),
+            wallet: tx.wallet.clone().unwrap_or_default(),
+            to: tx.to_address.clone(),
+            amount: tx.amount.clone(),
+            network: tx.network.clone(),
+            required_signatures: tx.threshold,
+            signature_count: tx.signature_count(),
+            signers: tx
+                .allowed_signers
+                .iter()
+                .flatten()
+                .map(|key| hex::encode(key.serialize()))
+                .collect(),
+            policy: tx.policy.clone(),
+            created_at: tx.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// 默认在触发前 7 天与 1 天各警告一次
+fn default_deadman_warning_secs() -> Vec<u64> {
+    vec![7 * 24 * 3600, 24 * 3600]
+}
+
+/// 最多可配置的警告次数
+pub const MAX_DEADMAN_WARNINGS: usize = 10;
+
+/// `POST /api/wallets/:name/deadman`（不实现 Debug，避免Password进日志）
+#[derive(Deserialize)]
+pub struct DeadmanPolicyRequest {
+    pub beneficiary: String,
+    pub networks: Vec<String>,
+    /// 无签到多少秒后触发
+    pub inactivity_secs: u64,
+    /// 触发前多少秒发出 `deadman.warning`
+    #[serde(default = "default_deadman_warning_secs")]
+    pub warning_secs: Vec<u64>,
+    /// 用于生成只能转给受益人的预授权sign能力
+    pub password: String,
+}
+
+/// [`DeadmanPolicyRequest`] validate后：network去重，警告按触发前秒数降序
+pub struct DeadmanPolicyConfig {
+    pub beneficiary: EvmAddress,
+    pub networks: Vec<NetworkName>,
+    pub inactivity_secs: u64,
+    pub warning_secs: Vec<u64>,
+    pub password: String,
+}
+
+impl Validate for DeadmanPolicyConfig {
+    type Raw = DeadmanPolicyRequest;
+
+    fn validate(raw: DeadmanPolicyRequest) -> Result<Self, ParamError> {
+        let beneficiary = EvmAddress::try_from(raw.beneficiary.as_str())?;
+        let mut networks: Vec<NetworkName> = Vec::new();
+        for name in &raw.networks {
+            let network = NetworkName::try_from(name.as_str())?.require_evm()?;
+            if !networks.contains(&network) {
+                networks.push(network);
+            }
+        }
+        if networks.is_empty() {
+            return Err(ParamError::Missing("networks"));
+        }
+        if raw.inactivity_secs == 0 || raw.inactivity_secs > i64::MAX as u64 {
+            return Err(ParamError::DeadmanPolicy("inactivity_secs must be a positive number of seconds".to_string()));
+        }
+        let mut warning_secs = raw.warning_secs;
+        warning_secs.sort_unstable_by(|a, b| b.cmp(a));
+        warning_secs.dedup();
+        if warning_secs.len() > MAX_DEADMAN_WARNINGS {
+            return Err(ParamError::DeadmanPolicy(format!("at most {} warnings", MAX_DEADMAN_WARNINGS)));
+        }
+        if warning_secs.iter().any(|w| *w == 0 || *w >= raw.inactivity_secs) {
+            return Err(ParamError::DeadmanPolicy(
+                "warning_secs must be between 1 and inactivity_secs - 1".to_string(),
+            ));
+        }
+        if raw.password.is_empty() {
+            return Err(ParamError::Missing("password"));
+        }
+        Ok(Self { beneficiary, networks, inactivity_secs: raw.inactivity_secs, warning_secs, password: raw.password })
+    }
+}
+
+/// `DELETE /api/wallets/:name/deadman`：取消需要wallet Password
+#[derive(Deserialize)]
+pub struct DeadmanCancelRequest {
+    pub password: String,
+}
+
+/// wallet的dead-man's switch（不含密封的sign能力）
+#[derive(Debug, Serialize)]
+pub struct DeadmanPolicyResponse {
+    #[serde(flatten)]
+    pub policy: crate::storage::DeadmanPolicy,
+    /// 不再签到时的触发时间（Unix 秒）
+    pub deadline: i64,
+}
+
+impl From<crate::storage::DeadmanPolicy> for DeadmanPolicyResponse {
+    fn from(policy: crate::storage::DeadmanPolicy) -> Self {
+        Self { deadline: policy.deadline(), policy }
+    }
+}
+
+/// `GET /api/validate/address?value=`
+#[derive(Debug, Deserialize)]
+pub struct ValidateAddressRequest {
+    pub value: Option<String>,
+}
+
+/// [`ValidateAddressRequest`] validate后：去掉首尾空白，非空
+#[derive(Debug, Clone)]
+pub struct ValidateAddressQuery {
+    pub value: String,
+}
+
+impl Validate for ValidateAddressQuery {
+    type Raw = ValidateAddressRequest;
+
+    fn validate(raw: ValidateAddressRequest) -> Result<Self, ParamError> {
+        let value = raw.value.as_deref().map(str::trim).filter(|v| !v.is_empty()).ok_or(ParamError::Missing("value"))?;
+        Ok(Self { value: value.to_string() })
+    }
+}
+
+/// 自定义 challenge 的最大字节数
+pub const MAX_ATTESTATION_CHALLENGE_LEN: usize = 2048;
+/// audience 的最大字符数
+pub const MAX_ATTESTATION_AUDIENCE_LEN: usize = 128;
+/// 有效期上限（一年）
+pub const MAX_ATTESTATION_TTL_SECS: u64 = 365 * 24 * 3600;
+
+/// `POST /api/wallets/:name/attestations`（不实现 Debug，避免Password进日志）
+#[derive(Deserialize)]
+pub struct AttestationRequest {
+    /// 默认 `eth`；`btc` 使用 Bitcoin 消息sign
+    pub network: Option<String>,
+    pub password: String,
+    /// 对方给出的 challenge；省略时生成包含address、时间、nonce 与 audience 的 challenge
+    pub challenge: Option<String>,
+    pub audience: Option<String>,
+    pub expires_in_secs: Option<u64>,
+}
+
+/// [`AttestationRequest`] validate后
+pub struct AttestationConfig {
+    /// 未指定时为 `eth`
+    pub network: Option<NetworkName>,
+    pub password: String,
+    pub challenge: Option<String>,
+    pub audience: Option<String>,
+    pub expires_in_secs: Option<u64>,
+}
+
+impl Validate for AttestationConfig {
+    type Raw = AttestationRequest;
+
+    fn validate(raw: AttestationRequest) -> Result<Self, ParamError> {
+        let network = raw.network.as_deref().map(NetworkName::try_from).transpose()?;
+        if raw.password.is_empty() {
+            return Err(ParamError::Missing("password"));
+        }
+        if let Some(challenge) = &raw.challenge {
+            if challenge.trim().is_empty() || challenge.len() > MAX_ATTESTATION_CHALLENGE_LEN {
+                return Err(ParamError::Attestation(format!(
+                    "challenge must be 1-{} bytes",
+                    MAX_ATTESTATION_CHALLENGE_LEN
+                )));
+            }
+        }
+        let audience = raw.audience.as_deref().map(str::trim).filter(|a| !a.is_empty());
+        if let Some(audience) = audience {
+            // audience 会写进生成的 challenge，按行拼接
+            if audience.chars().count() > MAX_ATTESTATION_AUDIENCE_LEN || audience.chars().any(char::is_control) {
+                return Err(ParamError::Attestation(format!(
+                    "audience must be at most {} printable characters",
+                    MAX_ATTESTATION_AUDIENCE_LEN
+                )));
+            }
+        }
+        if let Some(ttl) = raw.expires_in_secs {
+            if ttl == 0 || ttl > MAX_ATTESTATION_TTL_SECS {
+                return Err(ParamError::Attestation(format!(
+                    "expires_in_secs must be between 1 and {}",
+                    MAX_ATTESTATION_TTL_SECS
+                )));
+            }
+        }
+        Ok(Self {
+            network,
+            password: raw.password,
+            challenge: raw.challenge,
+            audience: audience.map(str::to_string),
+            expires_in_secs: raw.expires_in_secs,
+        })
+    }
+}
+
+/// 可携带的 attestation 文档；`POST /api/attestations/verify` 接受同一格式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestationDocument {
+    #[serde(default = "default_attestation_version")]
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// `eip191` | `bip137`
+    pub scheme: String,
+    pub network: String,
+    pub address: String,
+    pub challenge: String,
+    pub signature: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    /// Unix 秒
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+fn default_attestation_version() -> u32 {
+    1
+}
+
+impl From<&crate::storage::AttestationRecord> for AttestationDocument {
+    fn from(record: &crate::storage::AttestationRecord) -> Self {
+        Self {
+            version: default_attestation_version(),
+            id: Some(record.id.clone()),
+            scheme: record.scheme.clone(),
+            network: record.network.clone(),
+            address: record.address.clone(),
+            challenge: record.challenge.clone(),
+            signature: record.signature.clone(),
+            audience: record.audience.clone(),
+            created_at: record.created_at,
+            expires_at: record.expires_at,
+        }
+    }
+}
+
+/// 待validate的 attestation：只做长度上限check，签名本身由handler判定
+pub struct PresentedAttestation(pub AttestationDocument);
+
+impl Validate for PresentedAttestation {
+    type Raw = AttestationDocument;
+
+    fn validate(raw: AttestationDocument) -> Result<Self, ParamError> {
+        if raw.challenge.len() > MAX_ATTESTATION_CHALLENGE_LEN {
+            return Err(ParamError::Attestation(format!(
+                "challenge must be at most {} bytes",
+                MAX_ATTESTATION_CHALLENGE_LEN
+            )));
+        }
+        if raw.signature.len() > 256 || raw.address.len() > 128 {
+            return Err(ParamError::Attestation("signature or address is too long".to_string()));
+        }
+        Ok(Self(raw))
+    }
+}
+
+/// `POST /api/wallets/:name/attestations/:id/revoke`
+#[derive(Debug, Deserialize)]
+pub struct RevokeAttestationRequest {
+    pub reason: Option<String>,
+}
+
+/// [`RevokeAttestationRequest`] validate后：原因可选，最多 500 字符
+#[derive(Debug, Clone)]
+pub struct RevokeAttestation {
+    pub reason: Option<String>,
+}
+
+impl Validate for RevokeAttestation {
+    type Raw = RevokeAttestationRequest;
+
+    fn validate(raw: RevokeAttestationRequest) -> Result<Self, ParamError> {
+        let reason = raw.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+        if reason.is_some_and(|r| r.chars().count() > MAX_REJECT_REASON_LEN) {
+            return Err(ParamError::ReasonTooLong(MAX_REJECT_REASON_LEN));
+        }
+        Ok(Self { reason: reason.map(str::to_string) })
+    }
+}
+
+/// `POST /api/admin/users/:id/erase` 请求体
+#[derive(Debug, Default, Deserialize)]
+pub struct EraseUserRequest {
+    /// 写入擦除证书的操作人，缺省为 `admin`
+    pub requested_by: Option<String>,
+}
+
+/// `POST /api/admin/users/:id/erase`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserErasureResponse {
+    /// 本次运行是否改动了数据；重复擦除返回 false 和最近一张证书
+    pub changed: bool,
+    pub certificate: crate::storage::ErasureCertificate,
+}
+
+/// `GET /api/admin/users/:id/erasure-certificates`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErasureCertificatesResponse {
+    pub user_id: String,
+    /// 旧的在前
+    pub certificates: Vec<crate::storage::ErasureCertificate>,
+}
+
+/// `POST /api/admin/reports/proof_of_reserves` 请求体
+#[derive(Debug, Deserialize)]
+pub struct ReserveReportRequest {
+    /// 只统计该wallet组的成员；缺省为全部托管wallet
+    #[serde(default)]
+    pub group: Option<String>,
+    /// 签名wallet（`reserve_reports.signing_wallet`）的Password
+    #[serde(default)]
+    pub password: String,
+}
+
+/// [`ReserveReportRequest`] validate后
+#[derive(Debug)]
+pub struct ReserveReportParams {
+    pub group: Option<WalletNameParam>,
+    pub password: String,
+}
+
+impl Validate for ReserveReportParams {
+    type Raw = ReserveReportRequest;
+
+    fn validate(raw: ReserveReportRequest) -> Result<Self, ParamError> {
+        let group = raw.group.as_deref().map(WalletNameParam::try_from).transpose()?;
+        if raw.password.is_empty() {
+            return Err(ParamError::Missing("password"));
+        }
+        Ok(Self { group, password: raw.password })
+    }
+}
+
+/// `POST /api/admin/reconciliations` 请求体
+#[derive(Debug, Deserialize)]
+pub struct ReconciliationRequest {
+    /// Unix 秒；核对 `from..to` 期间创建的记录与上链的transaction
+    #[serde(default)]
+    pub from: Option<i64>,
+    #[serde(default)]
+    pub to: Option<i64>,
+    /// 缺省为全部配置的network
+    #[serde(default)]
+    pub networks: Vec<String>,
+    /// 修正可安全修正的差异（状态、缺失的手续费、缺失的记录）；缺省只出报告
+    #[serde(default)]
+    pub apply: bool,
+    /// 记入运行与审计日志的操作人，默认 `admin`
+    #[serde(default)]
+    pub requested_by: Option<String>,
+}
+
+/// [`ReconciliationRequest`] validate后
+#[derive(Debug)]
+pub struct ReconciliationParams {
+    pub from: i64,
+    pub to: i64,
+    pub networks: Vec<String>,
+    pub apply: bool,
+    pub requested_by: String,
+}
+
+impl Validate for ReconciliationParams {
+    type Raw = ReconciliationRequest;
+
+    fn validate(raw: ReconciliationRequest) -> Result<Self, ParamError> {
+        let from = raw.from.ok_or(ParamError::Missing("from"))?;
+        let to = raw.to.ok_or(ParamError::Missing("to"))?;
+        if from < 0 || from >= to {
+            return Err(ParamError::Reconciliation("from must be before to"));
+        }
+        let requested_by = raw.requested_by.as_deref().map(str::trim).filter(|r| !r.is_empty()).unwrap_or("admin");
+        Ok(Self { from, to, networks: raw.networks, apply: raw.apply, requested_by: requested_by.to_string() })
+    }
+}
+
+/// `POST /api/admin/reconciliations` 与 `GET /api/admin/reconciliations/:id`
+#[derive(Debug, Serialize)]
+pub struct ReconciliationResponse {
+    #[serde(flatten)]
+    pub run: crate::storage::ReconciliationRun,
+    /// 运行中的进度；只有正在执行它的实例能给出
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<crate::ops::jobs::JobProgress>,
+    /// 按发现顺序分页的差异（`discrepancies` 已是总数）；运行完成后才有
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub findings: Option<Page<crate::storage::StoredDiscrepancy>>,
+}
+
+/// 已签名的储备证明；`POST /api/reports/verify` 接受同一格式（`id` 可省略）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReserveReportDocument {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// 目前只有 `eip191`
+    pub scheme: String,
+    /// 签名address（EIP-55）
+    pub signer: String,
+    pub signature: String,
+    /// 被签名的报告（`ops::proof_of_reserves::ReserveReport`）；签名覆盖其 canonical JSON
+    pub report: serde_json::Value,
+}
+
+/// 待validate的储备证明：只checkscheme与必填字段，签名与合计由 handler 判定
+pub struct PresentedReserveReport(pub ReserveReportDocument);
+
+impl Validate for PresentedReserveReport {
+    type Raw = ReserveReportDocument;
+
+    fn validate(raw: ReserveReportDocument) -> Result<Self, ParamError> {
+        if raw.scheme != "eip191" {
+            return Err(ParamError::ReserveReport("scheme must be eip191"));
+        }
+        if !raw.report.is_object() {
+            return Err(ParamError::ReserveReport("report must be an object"));
+        }
+        Ok(Self(raw))
+    }
+}
+
+/// `GET /api/admin/reports/proof_of_reserves?page=1&page_size=50`
+#[derive(Debug, Serialize)]
+pub struct ReserveReportListResponse {
+    /// 最新在前；不含报告正文
+    pub reports: Vec<crate::storage::ReserveReportSummary>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+}
+
+/// `POST /api/wallets/:name/bundles`
+#[derive(Debug, Deserialize)]
+pub struct BundleSubmitRequest {
+    /// walletPassword：所有步骤都由服务端sign
+    #[serde(default)]
+    pub password: String,
+    /// `abort_remaining`（默认）或 `continue`
+    #[serde(default)]
+    pub failure_policy: Option<String>,
+    /// 按执行顺序：`{ "id", "kind", "depends_on"?, ... }`
+    #[serde(default)]
+    pub steps: Vec<serde_json::Value>,
+}
+
+/// [`BundleSubmitRequest`] validate后；依赖与占位符由 `BundleBuilder` 在 handler 中整体check
+#[derive(Debug)]
+pub struct BundleSubmission {
+    pub password: String,
+    pub failure_policy: FailurePolicy,
+    pub steps: Vec<BundleStep>,
+}
+
+impl Validate for BundleSubmission {
+    type Raw = BundleSubmitRequest;
+
+    fn validate(raw: BundleSubmitRequest) -> Result<Self, ParamError> {
+        if raw.password.is_empty() {
+            return Err(ParamError::Missing("password"));
+        }
+        let failure_policy =
+            raw.failure_policy.as_deref().map(str::parse).transpose().map_err(ParamError::Bundle)?.unwrap_or_default();
+        // 先限长再逐个解析
+        if raw.steps.len() > MAX_BUNDLE_STEPS {
+            return Err(ParamError::Bundle(BundleError::TooManySteps(MAX_BUNDLE_STEPS).to_string()));
+        }
+        let mut steps = Vec::with_capacity(raw.steps.len());
+        for (index, step) in raw.steps.into_iter().enumerate() {
+            let mut step: BundleStep =
+                serde_json::from_value(step).map_err(|e| ParamError::Bundle(format!("steps[{}]: {}", index, e)))?;
+            // network别名（ethereum、bnb…）统一为规范名，执行与限额都按规范名
+            for network in step.action.networks_mut() {
+                *network = NetworkName::try_from(network.as_str())?.require_evm()?.as_str().to_string();
+            }
+            steps.push(step);
+        }
+        Ok(Self { password: raw.password, failure_policy, steps })
+    }
+}
+
+/// `POST /api/bundles/:id/resume`
+#[derive(Debug, Deserialize)]
+pub struct BundleResumeRequest {
+    #[serde(default)]
+    pub password: String,
+}
+
+/// [`BundleResumeRequest`] validate后
+#[derive(Debug)]
+pub struct BundleResume {
+    pub password: String,
+}
+
+impl Validate for BundleResume {
+    type Raw = BundleResumeRequest;
+
+    fn validate(raw: BundleResumeRequest) -> Result<Self, ParamError> {
+        if raw.password.is_empty() {
+            return Err(ParamError::Missing("password"));
+        }
+        Ok(Self { password: raw.password })
+    }
+}
+
+/// 委托会话有效期上限（30 天）
+pub const MAX_DELEGATION_LIFETIME_SECS: u64 = 30 * 24 * 3600;
+/// 收款方白名单最多条目数
+pub const MAX_DELEGATION_RECIPIENTS: usize = 100;
+
+/// `POST /api/wallets/:name/delegations`（不实现 Debug，避免Password进日志）
+#[derive(Deserialize)]
+pub struct CreateDelegationRequest {
+    /// network → 总预算（最小单位十进制整数，如 wei）
+    pub budgets: std::collections::BTreeMap<String, String>,
+    /// 单笔上限（最小单位）
+    pub per_tx_cap: String,
+    pub expires_in_secs: u64,
+    /// 收款方白名单；省略表示任意收款方
+    pub recipients: Option<Vec<String>>,
+    /// 用于密封委托会话的sign密钥
+    pub password: String,
+}
+
+/// [`CreateDelegationRequest`] validate后：network为规范名，收款方小写去重
+pub struct CreateDelegation {
+    pub budgets: Vec<(NetworkName, ethers::types::U256)>,
+    pub per_tx_cap: ethers::types::U256,
+    pub expires_in_secs: u64,
+    pub recipients: Option<Vec<String>>,
+    pub password: String,
+}
+
+/// 最小单位金额：十进制整数、无前导零、大于 0
+fn parse_minimal_units(field: &str, value: &str) -> Result<ethers::types::U256, ParamError> {
+    let invalid = || ParamError::Delegation(format!("{} must be a positive integer in the smallest unit", field));
+    if value.is_empty() || value.len() > 78 || !value.bytes().all(|b| b.is_ascii_digit()) || value.starts_with('0') {
+        return Err(invalid());
+    }
+    ethers::types::U256::from_dec_str(value).map_err(|_| invalid())
+}
+
+impl Validate for CreateDelegation {
+    type Raw = CreateDelegationRequest;
+
+    fn validate(raw: CreateDelegationRequest) -> Result<Self, ParamError> {
+        let mut budgets: Vec<(NetworkName, ethers::types::U256)> = Vec::new();
+        for (name, total) in &raw.budgets {
+            let network = NetworkName::try_from(name.as_str())?.require_evm()?;
+            if budgets.iter().any(|(n, _)| *n == network) {
+                return Err(ParamError::Delegation(format!("duplicate budget for {}", network.as_str())));
+            }
+            budgets.push((network, parse_minimal_units("budget", total)?));
+        }
+        if budgets.is_empty() {
+            return Err(ParamError::Missing("budgets"));
+        }
+        let per_tx_cap = parse_minimal_units("per_tx_cap", &raw.per_tx_cap)?;
+        if raw.expires_in_secs == 0 || raw.expires_in_secs > MAX_DELEGATION_LIFETIME_SECS {
+            return Err(ParamError::TokenLifetime(MAX_DELEGATION_LIFETIME_SECS));
+        }
+        let recipients = match raw.recipients {
+            None => None,
+            Some(list) if list.is_empty() || list.len() > MAX_DELEGATION_RECIPIENTS => {
+                return Err(ParamError::Delegation(format!(
+                    "recipients must list 1-{} addresses, or be omitted to allow any",
+                    MAX_DELEGATION_RECIPIENTS
+                )));
+            }
+            Some(list) => {
+                let mut recipients: Vec<String> = Vec::with_capacity(list.len());
+                for address in &list {
+                    let address = EvmAddress::try_from(address.as_str())?.as_str().to_ascii_lowercase();
+                    if !recipients.contains(&address) {
+                        recipients.push(address);
+                    }
+                }
+                Some(recipients)
+            }
+        };
+        if raw.password.is_empty() {
+            return Err(ParamError::Missing("password"));
+        }
+        Ok(Self { budgets, per_tx_cap, expires_in_secs: raw.expires_in_secs, recipients, password: raw.password })
+    }
+}
+
+/// `POST /api/wallets/:name/delegations`：明文 token 只在此返回一次
+#[derive(Debug, Serialize)]
+pub struct DelegationCreatedResponse {
+    pub token: String,
+    #[serde(flatten)]
+    pub delegation: crate::storage::DelegationRecord,
+}
+
+/// `GET /api/wallets/:name/delegations`
+#[derive(Debug, Serialize)]
+pub struct DelegationListResponse {
+    pub delegations: Vec<crate::storage::DelegationRecord>,
+}
+
+/// 分页端点的固定参数：cursor 的签名作用域、扫描方向与每页条数
+pub trait PageScope {
+    const SCOPE: &'static str;
+    const DIRECTION: crate::api::pagination::PageDirection;
+    const DEFAULT_LIMIT: usize;
+    const MAX_LIMIT: usize;
+}
+
+/// 列表端点共用的 `?cursor=&limit=`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PageQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// [`PageQuery`] validate后：cursor 已验签且属于该端点，limit 已按端点上限截断
+#[derive(Debug, Clone)]
+pub struct PageRequest<S> {
+    pub cursor: Option<crate::api::pagination::PageCursor>,
+    pub limit: usize,
+    /// 请求中带了 `cursor` 或 `limit`
+    pub explicit: bool,
+    scope: std::marker::PhantomData<fn() -> S>,
+}
+
+impl<S: PageScope> Validate for PageRequest<S> {
+    type Raw = PageQuery;
+
+    fn validate(raw: PageQuery) -> Result<Self, ParamError> {
+        let explicit = raw.cursor.is_some() || raw.limit.is_some();
+        let cursor = raw
+            .cursor
+            .as_deref()
+            .filter(|c| !c.is_empty())
+            .map(|c| {
+                crate::api::pagination::PageCursor::decode(c, S::SCOPE)
+                    .ok()
+                    .filter(|c| c.direction == S::DIRECTION)
+                    .ok_or(ParamError::Cursor)
+            })
+            .transpose()?;
+        let limit = raw.limit.map_or(S::DEFAULT_LIMIT, |l| l.clamp(1, S::MAX_LIMIT as i64) as usize);
+        Ok(Self { cursor, limit, explicit, scope: std::marker::PhantomData })
+    }
+}
+
+impl<S: PageScope> PageRequest<S> {
+    /// 组装响应；`next` 为本页最后一行的位置，最后一页传 `None`
+    pub fn page<T>(
+        &self,
+        items: Vec<T>,
+        next: Option<crate::api::pagination::PageCursor>,
+        total: Option<u64>,
+    ) -> anyhow::Result<Page<T>> {
+        let next_cursor = next.map(|c| c.encode(S::SCOPE)).transpose()?;
+        Ok(Page { items, next_cursor, total })
+    }
+}
+
+/// 列表端点共用的响应信封
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// 下一页游标；最后一页省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// 匹配总数；只有计数代价低的端点返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
 }
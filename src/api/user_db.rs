@@ -8,7 +8,7 @@
 //! - Account lockout mechanism
 //! - Token expiration management
 
-use sqlx::{SqlitePool, sqlite::{SqlitePoolOptions, SqliteConnectOptions}};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool, sqlite::{SqlitePoolOptions, SqliteConnectOptions}};
 use std::str::FromStr;
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
@@ -44,6 +44,15 @@ pub struct WalletInfo {
     pub created_at: String,
 }
 
+/// Wallet list search: a `LIKE` pattern over the wallet name, plus the
+/// names the wallet database matched on description or metadata
+#[derive(Debug, Clone, Default)]
+pub struct WalletSearch {
+    /// Built by [`crate::storage::like_pattern`]; escaped with `\`
+    pub pattern: String,
+    pub names: Vec<String>,
+}
+
 /// Create user request payload
 #[derive(Debug, Deserialize)]
 pub struct CreateUserRequest {
@@ -52,6 +61,24 @@ pub struct CreateUserRequest {
     pub username: Option<String>,
 }
 
+/// Owner of wallets whose user has been erased (cannot log in)
+pub const ERASED_OWNER_ID: &str = "erased-owner-0000-0000-000000000000";
+
+/// Stored instead of a password hash on erased accounts; not a PHC string, so no password verifies
+const UNUSABLE_PASSWORD_HASH: &str =
+    "!erased!erased!erased!erased!erased!erased!erased!erased!erased!erased";
+
+/// users.db rows changed by one erasure run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserErasure {
+    /// Email, username and password hash replaced; `false` if already anonymized
+    pub profile_anonymized: bool,
+    /// `(user_wallets id, wallet name)` of the links handed to [`ERASED_OWNER_ID`]
+    pub wallets_transferred: Vec<(i64, String)>,
+    pub sessions_deleted: u64,
+    pub preferences_deleted: u64,
+}
+
 /// Login request payload
 #[derive(Debug, Deserialize)]
 pub struct LoginRequest {
@@ -99,6 +126,10 @@ impl UserDatabase {
             .execute(&pool)
             .await
             .context("Failed to run users database migrations")?;
+        sqlx::query(include_str!("../../migrations/users/002_user_wallet_epochs.sql"))
+            .execute(&pool)
+            .await
+            .context("Failed to run users database migrations")?;
         
         tracing::info!("✅ User database initialization complete");
 
@@ -385,6 +416,122 @@ impl UserDatabase {
         }).collect())
     }
     
+    /// Keyset page of a user's wallets ordered by `(created_at, id)`.
+    ///
+    /// `after` is the `(created_at, id)` key of the last row already returned.
+    /// Returns the rows plus the key to resume from when more rows exist.
+    pub async fn get_user_wallets_page(
+        &self,
+        user_id: &str,
+        after: Option<(&str, i64)>,
+        limit: usize,
+    ) -> Result<(Vec<WalletInfo>, Option<(String, i64)>)> {
+        self.search_user_wallets_page(user_id, None, after, limit).await
+    }
+
+    /// [`Self::get_user_wallets_page`] restricted to wallets matching `search`.
+    pub async fn search_user_wallets_page(
+        &self,
+        user_id: &str,
+        search: Option<&WalletSearch>,
+        after: Option<(&str, i64)>,
+        limit: usize,
+    ) -> Result<(Vec<WalletInfo>, Option<(String, i64)>)> {
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT id, wallet_name, wallet_address, wallet_type, created_at FROM user_wallets WHERE user_id = ",
+        );
+        qb.push_bind(user_id);
+        if let Some(search) = search {
+            // Names matched on the wallet side (description, metadata) travel
+            // as one JSON array, so the bind count does not grow with them.
+            qb.push(" AND (wallet_name LIKE ")
+                .push_bind(search.pattern.as_str())
+                .push(" ESCAPE '\\' OR wallet_name IN (SELECT value FROM json_each(")
+                .push_bind(serde_json::to_string(&search.names)?)
+                .push(")))");
+        }
+        if let Some((created_at, id)) = after {
+            qb.push(" AND (created_at, id) > (").push_bind(created_at).push(", ").push_bind(id).push(")");
+        }
+        qb.push(" ORDER BY created_at, id LIMIT ").push_bind(limit as i64 + 1);
+        let mut rows = qb
+            .build_query_as::<(i64, String, Option<String>, Option<String>, String)>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+        let next = if has_more {
+            rows.last().map(|r| (r.4.clone(), r.0))
+        } else {
+            None
+        };
+
+        Ok((
+            rows.into_iter()
+                .map(|row| WalletInfo {
+                    name: row.1,
+                    address: row.2,
+                    wallet_type: row.3.unwrap_or_else(|| "standard".to_string()),
+                    created_at: row.4,
+                })
+                .collect(),
+            next,
+        ))
+    }
+
+    /// `(id, wallet_address)` of every linked wallet that has an address, across all users
+    ///
+    /// Used by background jobs (balance snapshots); `id` is the user_wallets row id.
+    pub async fn list_wallet_addresses(&self) -> Result<Vec<(i64, String)>> {
+        let rows = sqlx::query_as::<_, (i64, String)>(
+            "SELECT id, wallet_address FROM user_wallets \
+             WHERE wallet_address IS NOT NULL AND wallet_address <> '' ORDER BY id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// `(wallet_name, wallet_address)` of every linked wallet that has an address, across all users
+    pub async fn list_linked_wallets(&self) -> Result<Vec<(String, String)>> {
+        let rows = sqlx::query_as::<_, (String, String)>(
+            "SELECT wallet_name, wallet_address FROM user_wallets \
+             WHERE wallet_address IS NOT NULL AND wallet_address <> '' ORDER BY wallet_name, id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// `(user_id, wallet_name)` of the wallet linked to `address` (any user, case-insensitive)
+    pub async fn find_wallet_by_address(&self, address: &str) -> Result<Option<(String, String)>> {
+        let row = sqlx::query_as::<_, (String, String)>(
+            "SELECT user_id, wallet_name FROM user_wallets \
+             WHERE wallet_address = ? COLLATE NOCASE ORDER BY id LIMIT 1"
+        )
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    /// `(id, wallet_address)` of one of the user's wallets
+    pub async fn find_user_wallet(&self, user_id: &str, wallet_name: &str) -> Result<Option<(i64, Option<String>)>> {
+        let row = sqlx::query_as::<_, (i64, Option<String>)>(
+            "SELECT id, wallet_address FROM user_wallets WHERE user_id = ? AND wallet_name = ?"
+        )
+        .bind(user_id)
+        .bind(wallet_name)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
     /// Delete user-wallet association (non-custodial mode)
     ///
     /// Only deletes association record from user_wallets table, doesn't affect actual blockchain wallet
@@ -400,7 +547,121 @@ impl UserDatabase {
         // Return whether any record was deleted
         Ok(result.rows_affected() > 0)
     }
-    
+
+    /// Version of a user's wallet list; triggers on `user_wallets` advance it
+    /// in the same transaction as every link, rename or unlink (0 before the first).
+    pub async fn wallet_list_epoch(&self, user_id: &str) -> Result<i64> {
+        let epoch: Option<i64> = sqlx::query_scalar("SELECT epoch FROM user_wallet_epochs WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(epoch.unwrap_or(0))
+    }
+
+    /// Whether a user row with this id exists (erased users included)
+    pub async fn user_exists(&self, user_id: &str) -> Result<bool> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = ?)")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(exists)
+    }
+
+    /// Erase a user's personal data in one transaction (None if the user doesn't exist)
+    ///
+    /// Wallet links move to [`ERASED_OWNER_ID`] (a name the erased owner already
+    /// holds gets a `#<link id>` suffix), sessions and preferences are deleted and
+    /// the user row is kept only as an anonymized, inactive tombstone. Running it
+    /// again on an erased user changes nothing.
+    pub async fn erase_user(&self, user_id: &str) -> Result<Option<UserErasure>> {
+        let mut tx = self.pool.begin().await?;
+
+        let email: Option<String> = sqlx::query_scalar("SELECT email FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(email) = email else {
+            return Ok(None);
+        };
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO users (id, email, username, password_hash, is_active) VALUES (?, ?, NULL, ?, 0)"
+        )
+        .bind(ERASED_OWNER_ID)
+        .bind(format!("{}@erased.invalid", ERASED_OWNER_ID))
+        .bind(UNUSABLE_PASSWORD_HASH)
+        .execute(&mut *tx)
+        .await?;
+
+        let wallets_transferred = sqlx::query_as::<_, (i64, String)>(
+            "SELECT id, wallet_name FROM user_wallets WHERE user_id = ? ORDER BY id"
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for (id, name) in &wallets_transferred {
+            let taken: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM user_wallets WHERE user_id = ? AND wallet_name = ?)"
+            )
+            .bind(ERASED_OWNER_ID)
+            .bind(name)
+            .fetch_one(&mut *tx)
+            .await?;
+            let new_name = if taken { format!("{}#{}", name, id) } else { name.clone() };
+            sqlx::query("UPDATE user_wallets SET user_id = ?, wallet_name = ? WHERE id = ?")
+                .bind(ERASED_OWNER_ID)
+                .bind(new_name)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let sessions_deleted = sqlx::query("DELETE FROM sessions WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        // user_preferences only exists once migration 004 has run
+        let has_preferences: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'user_preferences')"
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let preferences_deleted = if has_preferences {
+            sqlx::query("DELETE FROM user_preferences WHERE user_id = ?")
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+        } else {
+            0
+        };
+
+        let erased_email = format!("erased-{}@erased.invalid", user_id);
+        let profile_anonymized = email != erased_email;
+        if profile_anonymized {
+            sqlx::query(
+                "UPDATE users SET email = ?, username = NULL, password_hash = ?, is_active = 0, \
+                 last_login_at = NULL, failed_login_attempts = 0, locked_until = NULL WHERE id = ?"
+            )
+            .bind(&erased_email)
+            .bind(UNUSABLE_PASSWORD_HASH)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        tracing::info!(
+            "✅ User erased: {} ({} wallet links transferred)",
+            user_id,
+            wallets_transferred.len()
+        );
+
+        Ok(Some(UserErasure { profile_anonymized, wallets_transferred, sessions_deleted, preferences_deleted }))
+    }
+
     /// Update user password
     pub async fn update_password(&self, email: &str, new_password: &str) -> Result<()> {
         // Validate password strength
@@ -470,10 +731,9 @@ impl UserDatabase {
         use crate::api::validators::validate_password_strength;
         
         validate_password_strength(password)
-            .map_err(|(_, json)| {
+            .map_err(|e| {
                 // Convert ValidationResult error to anyhow::Error
-                let error_msg = &json.0.error;
-                anyhow::anyhow!("{}", error_msg)
+                anyhow::anyhow!("{}", e.message())
             })
     }
     
@@ -18,7 +18,7 @@ use std::sync::Arc;
 /// - 字段不存在 -> None
 /// - 字段存在且值为null -> Some(None)
 /// - 字段存在且值为"value" -> Some(Some("value"))
-fn deserialize_double_option<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
+pub(crate) fn deserialize_double_option<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -36,6 +36,9 @@ pub struct UserPreferences {
     pub notifications_enabled: bool,
     #[serde(default)]
     pub two_fa_enabled: bool,
+    /// 法币估值的计价货币（如 `usd`）；`None` 时使用服务端默认值
+    #[serde(default)]
+    pub preferred_currency: Option<String>,
     pub updated_at: i64,
     pub created_at: i64,
 }
@@ -53,6 +56,9 @@ pub struct UpdatePreferenceRequest {
     pub theme: Option<String>,
     pub language: Option<String>,
     pub notifications_enabled: Option<bool>,
+    /// 同 last_selected_wallet：null 表示恢复服务端默认货币
+    #[serde(default, deserialize_with = "deserialize_double_option")]
+    pub preferred_currency: Option<Option<String>>,
 }
 
 /// API响应包装
@@ -99,6 +105,7 @@ pub async fn get_user_preferences(
             language,
             notifications_enabled,
             two_fa_enabled,
+            preferred_currency,
             updated_at,
             created_at
         FROM user_preferences 
@@ -147,6 +154,7 @@ pub async fn get_user_preferences(
                 language: "en-US".to_string(),
                 notifications_enabled: true,
                 two_fa_enabled: false,
+                preferred_currency: None,
                 updated_at: now,
                 created_at: now,
             };
@@ -224,6 +232,16 @@ pub async fn update_user_preferences(
         updates.push("notifications_enabled = ?");
         has_updates = true;
     }
+    if let Some(currency) = &req.preferred_currency {
+        if currency.as_deref().is_some_and(|c| !is_currency_code(c)) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("Invalid currency code (expected e.g. usd, eur)".to_string())),
+            ));
+        }
+        updates.push("preferred_currency = ?");
+        has_updates = true;
+    }
 
     if !has_updates {
         // 没有更新，直接返回当前偏好
@@ -257,6 +275,9 @@ pub async fn update_user_preferences(
     if let Some(notify) = req.notifications_enabled {
         query = query.bind(if notify { 1 } else { 0 });
     }
+    if let Some(currency) = &req.preferred_currency {
+        query = query.bind(currency.as_deref().map(str::to_ascii_lowercase));
+    }
 
     query = query.bind(now).bind(&user_id);
 
@@ -271,6 +292,21 @@ pub async fn update_user_preferences(
     get_user_preferences(Extension(user_db), Path(user_id)).await
 }
 
+/// 价格源的计价货币代码：3-5 个 ASCII 字母（`usd`、`eur`、`btc`）
+pub fn is_currency_code(code: &str) -> bool {
+    (3..=5).contains(&code.len()) && code.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+/// user设置的计价货币（小写）；没有偏好记录或未设置时 `None`
+pub async fn preferred_currency(pool: &sqlx::SqlitePool, user_id: &str) -> anyhow::Result<Option<String>> {
+    let currency: Option<Option<String>> =
+        sqlx::query_scalar("SELECT preferred_currency FROM user_preferences WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(currency.flatten())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -287,5 +323,14 @@ mod tests {
         assert_eq!(error.data, None);
         assert_eq!(error.error, Some("Error".to_string()));
     }
+
+    #[test]
+    fn test_is_currency_code() {
+        assert!(is_currency_code("usd"));
+        assert!(is_currency_code("EUR"));
+        assert!(!is_currency_code("us"));
+        assert!(!is_currency_code("usd1"));
+        assert!(!is_currency_code("dollars"));
+    }
 }
 
@@ -2,104 +2,39 @@
 //! 
 //! 提供统一的validate逻辑，避免在各个handler中重复代码
 
-use axum::{http::StatusCode, response::Json};
-use crate::api::types::ErrorResponse;
+use crate::api::error_catalog::ApiErrorCode;
+use crate::api::types::ApiError;
+
+mod extract;
+mod params;
+
+pub use extract::{Validate, ValidJson, ValidPath, ValidQuery};
+pub use params::{
+    parse_derivation_path, Amount, EvmAddress, NetworkName, ParamError, TokenUnits, TxHash, WalletNameParam,
+    MAX_AMOUNT_DECIMALS, MAX_AMOUNT_LEN, MAX_WALLET_NAME_LEN,
+};
 
 /// validate结果类型（统一error响应）
-pub type ValidationResult = Result<(), (StatusCode, Json<ErrorResponse>)>;
+pub type ValidationResult = Result<(), ApiError>;
 
 /// validateWallet name
 /// 
-/// 规则：
+/// 规则见 [`WalletNameParam`]：
 /// - 不能为空
 /// - 长度限制：1-64字符
-/// - 只能包含字母、数字、下划线
+/// - 只能包含ASCII字母、数字、下划线、连字符
 /// - 不能包含路径遍历字符（../ 等）
-/// - 不能包含特殊字符（防XSS和注入）
 pub fn validate_wallet_name(name: &str) -> ValidationResult {
-    if name.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Wallet name cannot be empty".to_string(),
-                code: "INVALID_WALLET_NAME".to_string(),
-            }),
-        ));
-    }
-
-    // Length limit: maximum 64 characters
-    if name.len() > 64 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Wallet name too long (max 64 characters)".to_string(),
-                code: "WALLET_NAME_TOO_LONG".to_string(),
-            }),
-        ));
-    }
-
-    // Path traversal protection
-    if name.contains("..") || name.contains('/') || name.contains('\\') {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Wallet name contains invalid characters (path traversal detected)".to_string(),
-                code: "INVALID_WALLET_NAME".to_string(),
-            }),
-        ));
-    }
-
-    // Only allow letters, numbers, underscores, and hyphens
-    if name.contains(|c: char| !c.is_alphanumeric() && c != '_' && c != '-') {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid wallet name: must contain only letters, numbers, underscores, and hyphens".to_string(),
-                code: "INVALID_WALLET_NAME".to_string(),
-            }),
-        ));
-    }
-
-    Ok(())
+    WalletNameParam::try_from(name).map(|_| ()).map_err(Into::into)
 }
 
 /// Validate network parameter
 ///
-/// Rules:
-/// - Cannot be empty
-/// - Must be a supported network
+/// Rules: see [`NetworkName`] (canonical names plus aliases)
 ///
 /// Returns normalized network ID
-pub fn validate_and_normalize_network(network: &str) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
-    if network.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Network parameter is required".to_string(),
-                code: "INVALID_NETWORK".to_string(),
-            }),
-        ));
-    }
-
-    // Normalize network name (supports aliases)
-    let normalized = match network {
-        "ethereum" => "eth",
-        "binance" | "bnb" => "bsc",
-        "bitcoin" => "btc",
-        // Already a canonical name
-        "eth" | "sepolia" | "polygon" | "bsc" | "btc" => network,
-        _ => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("Unsupported network: {}. Supported: eth, btc, bsc, polygon, sepolia", network),
-                    code: "UNSUPPORTED_NETWORK".to_string(),
-                }),
-            ));
-        }
-    };
-
-    Ok(normalized.to_string())
+pub fn validate_and_normalize_network(network: &str) -> Result<String, ApiError> {
+    NetworkName::try_from(network).map(|n| n.as_str().to_string()).map_err(Into::into)
 }
 
 /// Validate required parameter is not empty
@@ -107,13 +42,7 @@ pub fn validate_and_normalize_network(network: &str) -> Result<String, (StatusCo
 /// Used to verify string parameters are not empty
 pub fn validate_required_param(param: &str, param_name: &str) -> ValidationResult {
     if param.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: format!("{} is required", param_name),
-                code: "MISSING_PARAMETER".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::MissingParameter, format!("{} is required", param_name)));
     }
     Ok(())
 }
@@ -129,46 +58,22 @@ pub fn validate_required_param(param: &str, param_name: &str) -> ValidationResul
 pub fn validate_password_strength(password: &str) -> ValidationResult {
     // Check minimum length
     if password.len() < 8 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Password must be at least 8 characters".to_string(),
-                code: "WEAK_PASSWORD".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::WeakPassword, "Password must be at least 8 characters"));
     }
     
     // Check for uppercase letter requirement
     if !password.chars().any(|c| c.is_ascii_uppercase()) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Password must contain at least one uppercase letter".to_string(),
-                code: "WEAK_PASSWORD".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::WeakPassword, "Password must contain at least one uppercase letter"));
     }
     
     // Check for lowercase letter requirement
     if !password.chars().any(|c| c.is_ascii_lowercase()) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Password must contain at least one lowercase letter".to_string(),
-                code: "WEAK_PASSWORD".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::WeakPassword, "Password must contain at least one lowercase letter"));
     }
     
     // Check for digit requirement
     if !password.chars().any(|c| c.is_ascii_digit()) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Password must contain at least one digit".to_string(),
-                code: "WEAK_PASSWORD".to_string(),
-            }),
-        ));
+        return Err(ApiError::new(ApiErrorCode::WeakPassword, "Password must contain at least one digit"));
     }
     
     // Check for special character requirement
@@ -177,12 +82,9 @@ pub fn validate_password_strength(password: &str) -> ValidationResult {
     });
     
     if !has_special {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Password must contain at least one special character (!@#$%^&* etc.)".to_string(),
-                code: "WEAK_PASSWORD".to_string(),
-            }),
+        return Err(ApiError::new(
+            ApiErrorCode::WeakPassword,
+            "Password must contain at least one special character (!@#$%^&* etc.)",
         ));
     }
     
@@ -191,87 +93,22 @@ pub fn validate_password_strength(password: &str) -> ValidationResult {
 
 /// Validate transaction amount
 /// 
-/// Rules:
-/// - Must be a valid number
+/// Rules: see [`Amount`]
+/// - Plain decimal, no sign / exponent / leading zeros
 /// - Must be greater than 0
 /// - Precision limit (maximum 18 decimal places)
 pub fn validate_transaction_amount(amount: &str) -> ValidationResult {
-    // Parse as floating point number
-    let parsed_amount: f64 = amount.parse().map_err(|_| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid amount format".to_string(),
-                code: "INVALID_AMOUNT".to_string(),
-            }),
-        )
-    })?;
-
-    // ✅ 必须大于0
-    if parsed_amount <= 0.0 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Amount must be greater than 0".to_string(),
-                code: "INVALID_AMOUNT".to_string(),
-            }),
-        ));
-    }
-
-    // ✅ 防止无穷大和NaN
-    if !parsed_amount.is_finite() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid amount value".to_string(),
-                code: "INVALID_AMOUNT".to_string(),
-            }),
-        ));
-    }
-
-    Ok(())
+    Amount::try_from(amount).map(|_| ()).map_err(Into::into)
 }
 
 /// validatewalletaddress格式
 /// 
-/// 规则：
+/// 规则见 [`EvmAddress`]：
 /// - 必须以0x开头
 /// - 总长度42字符（0x + 40位十六进制）
-/// - 只包含十六进制字符
+/// - 大小写混合时必须符合 EIP-55 校验和
 pub fn validate_wallet_address(address: &str) -> ValidationResult {
-    if !address.starts_with("0x") {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Wallet address must start with 0x".to_string(),
-                code: "INVALID_ADDRESS".to_string(),
-            }),
-        ));
-    }
-
-    if address.len() != 42 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Wallet address must be 42 characters long (0x + 40 hex digits)".to_string(),
-                code: "INVALID_ADDRESS".to_string(),
-            }),
-        ));
-    }
-
-    // validate十六进制字符
-    let hex_part = &address[2..];
-    if !hex_part.chars().all(|c| c.is_ascii_hexdigit()) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Wallet address must contain only hexadecimal characters".to_string(),
-                code: "INVALID_ADDRESS".to_string(),
-            }),
-        ));
-    }
-
-    Ok(())
+    EvmAddress::try_from(address).map(|_| ()).map_err(Into::into)
 }
 
 #[cfg(test)]
@@ -0,0 +1,115 @@
+//! Validating axum extractors
+//!
+//! Rejections are returned as the usual `ApiError`
+//! pair, so a handler body only ever sees typed, validated input.
+
+use axum::{
+    async_trait,
+    extract::{FromRequest, FromRequestParts, Path, Query, Request},
+    http::{request::Parts, StatusCode},
+    response::Json,
+};
+use serde::de::DeserializeOwned;
+
+use super::params::ParamError;
+use crate::api::types::ApiError;
+
+/// A request payload with a raw (wire) form and a validated form
+pub trait Validate: Sized {
+    type Raw: DeserializeOwned;
+
+    fn validate(raw: Self::Raw) -> Result<Self, ParamError>;
+}
+
+/// Single path parameter parsed through `TryFrom<&str>`
+#[derive(Debug, Clone)]
+pub struct ValidPath<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for ValidPath<T>
+where
+    S: Send + Sync,
+    T: for<'a> TryFrom<&'a str, Error = ParamError>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(raw) = Path::<String>::from_request_parts(parts, state).await.map_err(|e| {
+            ParamError::Malformed { status: StatusCode::BAD_REQUEST, message: e.body_text() }
+        })?;
+        Ok(Self(T::try_from(raw.as_str())?))
+    }
+}
+
+/// JSON body deserialized as `T::Raw`, then validated into `T`
+#[derive(Debug, Clone)]
+pub struct ValidJson<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for ValidJson<T>
+where
+    S: Send + Sync,
+    T: Validate,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(raw) = Json::<T::Raw>::from_request(req, state)
+            .await
+            .map_err(|e| ParamError::Malformed { status: e.status(), message: e.body_text() })?;
+        Ok(Self(T::validate(raw)?))
+    }
+}
+
+/// Query string deserialized as `T::Raw`, then validated into `T`
+#[derive(Debug, Clone)]
+pub struct ValidQuery<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for ValidQuery<T>
+where
+    S: Send + Sync,
+    T: Validate,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Query decodes lossily; a value must not silently become U+FFFD
+        if !parts.uri.query().is_none_or(decodes_to_utf8) {
+            return Err(ParamError::Malformed {
+                status: StatusCode::BAD_REQUEST,
+                message: "Query string is not valid percent-encoded UTF-8".to_string(),
+            }
+            .into());
+        }
+        let Query(raw) = Query::<T::Raw>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| ParamError::Malformed { status: StatusCode::BAD_REQUEST, message: e.body_text() })?;
+        Ok(Self(T::validate(raw)?))
+    }
+}
+
+/// Whether every `%XX` escape in `query` decodes to valid UTF-8
+fn decodes_to_utf8(query: &str) -> bool {
+    let bytes = query.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    std::str::from_utf8(&decoded).is_ok()
+}
@@ -0,0 +1,533 @@
+//! Typed request parameters
+//!
+//! Each type is only constructible through `TryFrom<&str>`, so a value in hand
+//! has already passed the grammar documented on the type. Failures map to a
+//! [`ParamError`] whose `code()` is part of the API contract.
+
+use axum::http::StatusCode;
+use ethers::types::{Address, U256};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::core::abi::abi_word_uint256_from_str;
+use crate::core::errors::WalletError;
+use crate::core::validation::{detect_address, ChainFamily};
+use crate::core::wallet_manager::derivation::DerivationPath;
+
+/// Maximum wallet name length in bytes
+pub const MAX_WALLET_NAME_LEN: usize = 64;
+/// Maximum decimal places of an [`Amount`] (wei precision)
+pub const MAX_AMOUNT_DECIMALS: usize = 18;
+/// Upper bound on the textual length of an [`Amount`]; checked before parsing
+pub const MAX_AMOUNT_LEN: usize = 80;
+
+/// Why a request parameter was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParamError {
+    #[error("{0} is required")]
+    Missing(&'static str),
+
+    #[error("Wallet name cannot be empty")]
+    WalletNameEmpty,
+    #[error("Wallet name too long (max 64 characters)")]
+    WalletNameTooLong,
+    #[error("Wallet name contains invalid characters (path traversal detected)")]
+    WalletNamePathTraversal,
+    #[error("Invalid wallet name: must contain only letters, numbers, underscores, and hyphens")]
+    WalletNameCharset,
+
+    #[error("Wallet address must start with 0x")]
+    AddressPrefix,
+    #[error("Wallet address must be 42 characters long (0x + 40 hex digits)")]
+    AddressLength,
+    #[error("Wallet address must contain only hexadecimal characters")]
+    AddressHex,
+    #[error("Wallet address has an invalid EIP-55 checksum")]
+    AddressChecksum,
+    #[error("Spender cannot be the zero address")]
+    ZeroSpender,
+    #[error("This looks like {0}; an EVM address (0x + 40 hex digits) is required")]
+    AddressWrongChain(String),
+
+    #[error("Invalid amount format")]
+    AmountFormat,
+    #[error("Amount is too long")]
+    AmountTooLong,
+    #[error("Amount must not have leading zeros")]
+    AmountLeadingZero,
+    #[error("Amount has more than 18 decimal places")]
+    AmountPrecision,
+    #[error("Amount must be greater than 0")]
+    AmountNotPositive,
+    #[error("Amount must be an integer in the token's smallest unit, at most 2^256 - 1")]
+    TokenUnits,
+
+    #[error("Network parameter is required")]
+    NetworkMissing,
+    #[error("Unsupported network. Supported: eth, sepolia, polygon, polygon-testnet, bsc, bsctestnet, btc")]
+    NetworkUnsupported,
+    #[error("Network {0} is not supported for this operation")]
+    NetworkNotAllowed(&'static str),
+
+    #[error("Transaction hash must be 64 hex digits, optionally prefixed with 0x")]
+    TxHashFormat,
+    #[error("{0} must be 0x-prefixed hex bytes")]
+    HexBytes(&'static str),
+
+    #[error("Unknown capability (expected read_history, read_balance or send)")]
+    CapabilityUnknown,
+    #[error("Token lifetime must be between 1 second and {0} seconds")]
+    TokenLifetime(u64),
+    #[error("Unknown auth_mode (expected bearer or hmac)")]
+    AuthMode,
+    #[error("Invalid multisig policy: {0}")]
+    MultisigPolicy(String),
+    #[error("Invalid spending limit: {0}")]
+    SpendingLimit(&'static str),
+    #[error("Window must be a positive number of hours or days (e.g. 24h, 30d), at most {0} days")]
+    Window(i64),
+    #[error("Unknown approval status (expected pending, approved, rejected, expired or failed)")]
+    ApprovalStatus,
+    #[error("Reason must be at most {0} characters")]
+    ReasonTooLong(usize),
+    #[error("Invalid cursor")]
+    Cursor,
+    #[error("min_delta must be a non-negative integer in the smallest unit (e.g. wei)")]
+    MinDelta,
+    #[error("webhook_target must be 1-{0} characters")]
+    WebhookTarget(usize),
+    #[error("Invalid dead-man policy: {0}")]
+    DeadmanPolicy(String),
+    #[error("Invalid attestation: {0}")]
+    Attestation(String),
+    #[error("Unknown Bitcoin address type (expected legacy, segwit or taproot)")]
+    BtcAddressType,
+    #[error("Account must be a BIP32 index below 2^31")]
+    BtcAccount,
+    #[error("{0}")]
+    DerivationPath(String),
+    #[error("{0}")]
+    PaymentUri(String),
+    #[error("payment_uri and {0} disagree")]
+    PaymentUriMismatch(&'static str),
+    #[error("{0}")]
+    PaymentUriUnsupported(&'static str),
+    #[error("No configured network has chain id {0}")]
+    ChainIdUnknown(u64),
+    #[error("Invalid reserve report: {0}")]
+    ReserveReport(&'static str),
+    #[error("Invalid bundle: {0}")]
+    Bundle(String),
+    #[error("Invalid batch: {0}")]
+    Batch(String),
+    #[error("dry_run {0}")]
+    DryRun(&'static str),
+    #[error("Invalid delegation: {0}")]
+    Delegation(String),
+    #[error("Invalid reconciliation: {0}")]
+    Reconciliation(&'static str),
+    #[error("Invalid wallet notes: {0}")]
+    WalletNotes(String),
+    #[error("Wallet notes too large: {0}")]
+    NotesTooLarge(String),
+    #[error("Metadata may hold at most {0} keys")]
+    MetadataKeys(usize),
+    #[error("{0} looks like key material or a seed phrase and cannot be stored")]
+    NotesSecret(String),
+
+    /// Body or query string could not be decoded at all
+    #[error("{message}")]
+    Malformed { status: StatusCode, message: String },
+}
+
+impl ParamError {
+    /// Stable error code returned in `error.code`
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParamError::Missing(_) => "MISSING_PARAMETER",
+            ParamError::WalletNameTooLong => "WALLET_NAME_TOO_LONG",
+            ParamError::WalletNameEmpty | ParamError::WalletNamePathTraversal | ParamError::WalletNameCharset => {
+                "INVALID_WALLET_NAME"
+            }
+            ParamError::AddressPrefix
+            | ParamError::AddressLength
+            | ParamError::AddressHex
+            | ParamError::ZeroSpender => "INVALID_ADDRESS",
+            ParamError::AddressChecksum => "INVALID_ADDRESS_CHECKSUM",
+            ParamError::AddressWrongChain(_) => "WRONG_CHAIN_ADDRESS",
+            ParamError::AmountFormat
+            | ParamError::AmountTooLong
+            | ParamError::AmountLeadingZero
+            | ParamError::AmountNotPositive
+            | ParamError::TokenUnits => "INVALID_AMOUNT",
+            ParamError::AmountPrecision => "AMOUNT_PRECISION_EXCEEDED",
+            ParamError::NetworkMissing => "INVALID_NETWORK",
+            ParamError::NetworkUnsupported | ParamError::NetworkNotAllowed(_) => "UNSUPPORTED_NETWORK",
+            ParamError::TxHashFormat => "INVALID_TX_HASH",
+            ParamError::HexBytes(_) => "INVALID_HEX",
+            ParamError::CapabilityUnknown => "INVALID_CAPABILITY",
+            ParamError::TokenLifetime(_) => "INVALID_TOKEN_LIFETIME",
+            ParamError::AuthMode => "INVALID_AUTH_MODE",
+            ParamError::MultisigPolicy(_) => "INVALID_MULTISIG_POLICY",
+            ParamError::SpendingLimit(_) => "INVALID_SPENDING_LIMIT",
+            ParamError::Window(_) => "INVALID_WINDOW",
+            ParamError::ApprovalStatus => "INVALID_STATUS",
+            ParamError::ReasonTooLong(_) => "REASON_TOO_LONG",
+            ParamError::Cursor => "INVALID_CURSOR",
+            ParamError::MinDelta => "INVALID_MIN_DELTA",
+            ParamError::WebhookTarget(_) => "INVALID_WEBHOOK_TARGET",
+            ParamError::DeadmanPolicy(_) => "INVALID_DEADMAN_POLICY",
+            ParamError::Attestation(_) => "INVALID_ATTESTATION",
+            ParamError::BtcAddressType => "INVALID_ADDRESS_TYPE",
+            ParamError::BtcAccount => "INVALID_ACCOUNT",
+            ParamError::DerivationPath(_) => "INVALID_DERIVATION_PATH",
+            ParamError::PaymentUri(_) => "INVALID_PAYMENT_URI",
+            ParamError::PaymentUriMismatch(_) => "PAYMENT_URI_MISMATCH",
+            ParamError::PaymentUriUnsupported(_) => "UNSUPPORTED_PAYMENT_URI",
+            ParamError::ChainIdUnknown(_) => "UNKNOWN_CHAIN_ID",
+            ParamError::ReserveReport(_) => "INVALID_RESERVE_REPORT",
+            ParamError::Bundle(_) => "INVALID_BUNDLE",
+            ParamError::Batch(_) => "INVALID_BATCH",
+            ParamError::DryRun(_) => "DRY_RUN_UNSUPPORTED",
+            ParamError::Delegation(_) => "INVALID_DELEGATION",
+            ParamError::Reconciliation(_) => "INVALID_RECONCILIATION",
+            ParamError::WalletNotes(_) => "INVALID_WALLET_NOTES",
+            ParamError::NotesTooLarge(_) => "WALLET_NOTES_TOO_LARGE",
+            ParamError::MetadataKeys(_) => "TOO_MANY_METADATA_KEYS",
+            ParamError::NotesSecret(_) => "SECRET_IN_WALLET_NOTES",
+            ParamError::Malformed { status, .. } => match *status {
+                StatusCode::UNPROCESSABLE_ENTITY => "INVALID_REQUEST_BODY",
+                StatusCode::UNSUPPORTED_MEDIA_TYPE => "UNSUPPORTED_MEDIA_TYPE",
+                StatusCode::PAYLOAD_TOO_LARGE => "PAYLOAD_TOO_LARGE",
+                _ => "INVALID_REQUEST",
+            },
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            // One status per code, as declared in `error_catalog`
+            ParamError::Malformed { status, .. }
+                if matches!(
+                    *status,
+                    StatusCode::UNPROCESSABLE_ENTITY
+                        | StatusCode::UNSUPPORTED_MEDIA_TYPE
+                        | StatusCode::PAYLOAD_TOO_LARGE
+                ) =>
+            {
+                *status
+            }
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// Wallet name: 1-64 ASCII letters, digits, `_` or `-`
+///
+/// Non-ASCII letters are rejected so look-alike names cannot shadow each other.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WalletNameParam(String);
+
+impl TryFrom<&str> for WalletNameParam {
+    type Error = ParamError;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        if name.is_empty() {
+            return Err(ParamError::WalletNameEmpty);
+        }
+        if name.len() > MAX_WALLET_NAME_LEN {
+            return Err(ParamError::WalletNameTooLong);
+        }
+        if name.contains("..") || name.contains('/') || name.contains('\\') {
+            return Err(ParamError::WalletNamePathTraversal);
+        }
+        if !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-') {
+            return Err(ParamError::WalletNameCharset);
+        }
+        Ok(Self(name.to_string()))
+    }
+}
+
+/// EVM address: `0x` + 40 hex digits
+///
+/// All-lowercase and all-uppercase hex is accepted as is; mixed case must be a
+/// valid EIP-55 checksum.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EvmAddress(String);
+
+impl TryFrom<&str> for EvmAddress {
+    type Error = ParamError;
+
+    /// Input that is another chain's address is reported as such
+    fn try_from(address: &str) -> Result<Self, Self::Error> {
+        Self::parse(address).map_err(|e| {
+            match detect_address(address).into_iter().find(|m| m.family != ChainFamily::Evm) {
+                Some(other) => ParamError::AddressWrongChain(other.describe()),
+                None => e,
+            }
+        })
+    }
+}
+
+impl EvmAddress {
+    fn parse(address: &str) -> Result<Self, ParamError> {
+        let hex = address.strip_prefix("0x").ok_or(ParamError::AddressPrefix)?;
+        if address.len() != 42 {
+            return Err(ParamError::AddressLength);
+        }
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ParamError::AddressHex);
+        }
+
+        let has_lower = hex.bytes().any(|b| b.is_ascii_lowercase());
+        let has_upper = hex.bytes().any(|b| b.is_ascii_uppercase());
+        if has_lower && has_upper {
+            let parsed = Address::from_str(address).map_err(|_| ParamError::AddressHex)?;
+            if ethers::utils::to_checksum(&parsed, None) != address {
+                return Err(ParamError::AddressChecksum);
+            }
+        }
+        Ok(Self(address.to_string()))
+    }
+}
+
+/// Positive decimal amount in whole units
+///
+/// Grammar: `int ["." frac]` where `int` is `0` or digits without a leading
+/// zero and `frac` is 1-18 digits. No sign, exponent, whitespace or grouping.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Amount(String);
+
+impl TryFrom<&str> for Amount {
+    type Error = ParamError;
+
+    fn try_from(amount: &str) -> Result<Self, Self::Error> {
+        if amount.len() > MAX_AMOUNT_LEN {
+            return Err(ParamError::AmountTooLong);
+        }
+        let (int, frac) = match amount.split_once('.') {
+            Some((int, frac)) => (int, Some(frac)),
+            None => (amount, None),
+        };
+        let all_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        if !all_digits(int) || !frac.is_none_or(all_digits) {
+            return Err(ParamError::AmountFormat);
+        }
+        if int.len() > 1 && int.starts_with('0') {
+            return Err(ParamError::AmountLeadingZero);
+        }
+        if frac.is_some_and(|f| f.len() > MAX_AMOUNT_DECIMALS) {
+            return Err(ParamError::AmountPrecision);
+        }
+        if amount.bytes().all(|b| b == b'0' || b == b'.') {
+            return Err(ParamError::AmountNotPositive);
+        }
+        Ok(Self(amount.to_string()))
+    }
+}
+
+/// Token amount in the token's smallest unit: `0` or digits without a
+/// leading zero, at most 2^256 - 1. Zero is allowed (revoking an approval).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenUnits(String);
+
+impl TryFrom<&str> for TokenUnits {
+    type Error = ParamError;
+
+    fn try_from(units: &str) -> Result<Self, Self::Error> {
+        if units.is_empty() || !units.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParamError::TokenUnits);
+        }
+        if units.len() > 1 && units.starts_with('0') {
+            return Err(ParamError::AmountLeadingZero);
+        }
+        abi_word_uint256_from_str(units).map_err(|_| ParamError::TokenUnits)?;
+        Ok(Self(units.to_string()))
+    }
+}
+
+impl TokenUnits {
+    pub fn value(&self) -> U256 {
+        // parsed in try_from
+        U256::from_dec_str(&self.0).unwrap_or_default()
+    }
+}
+
+/// Canonical network name
+///
+/// Accepts the canonical names plus the aliases `ethereum`, `binance`, `bnb`
+/// and `bitcoin`. Matching is exact (case-sensitive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NetworkName(&'static str);
+
+impl NetworkName {
+    pub fn is_evm(self) -> bool {
+        self.0 != "btc"
+    }
+
+    /// Rejects non-EVM networks for EVM-only operations
+    pub fn require_evm(self) -> Result<Self, ParamError> {
+        if self.is_evm() {
+            Ok(self)
+        } else {
+            Err(ParamError::NetworkNotAllowed(self.0))
+        }
+    }
+}
+
+impl TryFrom<&str> for NetworkName {
+    type Error = ParamError;
+
+    fn try_from(network: &str) -> Result<Self, Self::Error> {
+        let canonical = match network {
+            "" => return Err(ParamError::NetworkMissing),
+            "eth" | "ethereum" => "eth",
+            "sepolia" => "sepolia",
+            "polygon" => "polygon",
+            "polygon-testnet" => "polygon-testnet",
+            "bsc" | "binance" | "bnb" => "bsc",
+            "bsctestnet" => "bsctestnet",
+            "btc" | "bitcoin" => "btc",
+            _ => return Err(ParamError::NetworkUnsupported),
+        };
+        Ok(Self(canonical))
+    }
+}
+
+/// Transaction hash: 64 hex digits, optionally `0x`-prefixed (EVM hash or BTC txid)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TxHash(String);
+
+impl TryFrom<&str> for TxHash {
+    type Error = ParamError;
+
+    fn try_from(hash: &str) -> Result<Self, Self::Error> {
+        let hex = hash.strip_prefix("0x").unwrap_or(hash);
+        if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ParamError::TxHashFormat);
+        }
+        Ok(Self(hash.to_string()))
+    }
+}
+
+/// BIP32 derivation path (`m/44'/60'/0'/0/5`); rules in [`DerivationPath::parse`]
+pub fn parse_derivation_path(path: &str) -> Result<DerivationPath, ParamError> {
+    DerivationPath::parse(path).map_err(|e| match e {
+        WalletError::ValidationError(msg) => ParamError::DerivationPath(msg),
+        other => ParamError::DerivationPath(other.to_string()),
+    })
+}
+
+macro_rules! string_param {
+    ($($ty:ident),*) => {$(
+        impl $ty {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $ty {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                s.serialize_str(&self.0)
+            }
+        }
+    )*};
+}
+
+string_param!(WalletNameParam, EvmAddress, Amount, TokenUnits, NetworkName, TxHash);
+
+impl From<WalletNameParam> for String {
+    fn from(v: WalletNameParam) -> Self {
+        v.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wallet_name_rejects_non_ascii() {
+        assert!(WalletNameParam::try_from("wallet_1-a").is_ok());
+        // Cyrillic 'а' looks like Latin 'a'
+        assert_eq!(WalletNameParam::try_from("w\u{0430}llet"), Err(ParamError::WalletNameCharset));
+        assert_eq!(WalletNameParam::try_from("a\0b"), Err(ParamError::WalletNameCharset));
+        assert_eq!(WalletNameParam::try_from(&*"a".repeat(65)), Err(ParamError::WalletNameTooLong));
+    }
+
+    #[test]
+    fn test_token_units() {
+        assert_eq!(TokenUnits::try_from("0").unwrap().value(), U256::zero());
+        assert_eq!(TokenUnits::try_from("1500000").unwrap().value(), U256::from(1_500_000u64));
+        let max = U256::MAX.to_string();
+        assert_eq!(TokenUnits::try_from(max.as_str()).unwrap().value(), U256::MAX);
+        let too_big = "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+        assert_eq!(TokenUnits::try_from(too_big), Err(ParamError::TokenUnits));
+        for units in ["", "1.5", "-1", "1e6", "0x10", " 1"] {
+            assert_eq!(TokenUnits::try_from(units), Err(ParamError::TokenUnits), "{:?}", units);
+        }
+        assert_eq!(TokenUnits::try_from("007"), Err(ParamError::AmountLeadingZero));
+    }
+
+    #[test]
+    fn test_address_checksum() {
+        let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        assert!(EvmAddress::try_from(checksummed).is_ok());
+        assert!(EvmAddress::try_from(&*checksummed.to_lowercase()).is_ok());
+        assert!(EvmAddress::try_from("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED").is_ok());
+        assert_eq!(
+            EvmAddress::try_from("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Err(ParamError::AddressChecksum)
+        );
+        assert_eq!(EvmAddress::try_from("0X5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"), Err(ParamError::AddressPrefix));
+        if cfg!(feature = "bitcoin") {
+            assert_eq!(
+                EvmAddress::try_from("tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7"),
+                Err(ParamError::AddressWrongChain("a Bitcoin testnet address".to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_amount_grammar() {
+        for ok in ["1", "0.5", "10.000000000000000001", "1000000"] {
+            assert!(Amount::try_from(ok).is_ok(), "{}", ok);
+        }
+        assert_eq!(Amount::try_from("01"), Err(ParamError::AmountLeadingZero));
+        assert_eq!(Amount::try_from("0.0"), Err(ParamError::AmountNotPositive));
+        assert_eq!(Amount::try_from("1.0000000000000000001"), Err(ParamError::AmountPrecision));
+        for bad in ["", ".5", "5.", "-1", "+1", "1e18", " 1", "1,000", "NaN", "inf", "١"] {
+            assert_eq!(Amount::try_from(bad), Err(ParamError::AmountFormat), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_network_aliases() {
+        assert_eq!(NetworkName::try_from("ethereum").unwrap().as_str(), "eth");
+        assert_eq!(NetworkName::try_from("bnb").unwrap().as_str(), "bsc");
+        assert_eq!(NetworkName::try_from("ETH"), Err(ParamError::NetworkUnsupported));
+        assert_eq!(NetworkName::try_from(""), Err(ParamError::NetworkMissing));
+        assert!(NetworkName::try_from("btc").unwrap().require_evm().is_err());
+    }
+
+    #[test]
+    fn test_tx_hash() {
+        let h = "ab".repeat(32);
+        assert!(TxHash::try_from(h.as_str()).is_ok());
+        assert!(TxHash::try_from(format!("0x{}", h).as_str()).is_ok());
+        assert_eq!(TxHash::try_from(format!("0x{}0", h).as_str()), Err(ParamError::TxHashFormat));
+        assert_eq!(TxHash::try_from("0x"), Err(ParamError::TxHashFormat));
+    }
+}
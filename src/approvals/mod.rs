@@ -0,0 +1,292 @@
+//! Four-eyes approval of large server-signed sends
+//!
+//! A send above the wallet's review threshold is not signed. The requester's
+//! password unlocks the signer once, the unsigned transaction is stored as a
+//! `pending` approval, and the signing key is sealed to that transaction
+//! (see [`crate::security::approval_key`]) and stored with it. A second
+//! operator listed in `approvals.approvers` approves or rejects it; approving
+//! opens the key again for the normal signing path. Any instance sharing the
+//! database can execute the approval, also after a restart. The sealed key is
+//! deleted when the request leaves `pending`; a key that no longer opens (the
+//! envelope key was rotated since) fails the request, and the requester has
+//! to send again.
+
+use ethers::signers::LocalWallet;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::core::config::ApprovalConfig;
+use crate::security::approval_key::{ApprovalKey, HeldIntent};
+use crate::storage::{
+    ApprovalDecision, ApprovalPayload, ApprovalRecord, WalletStorage, APPROVAL_APPROVED, APPROVAL_EXPIRED,
+    APPROVAL_FAILED, APPROVAL_PENDING, APPROVAL_REJECTED,
+};
+
+/// Why an approval action was refused. `code()` is part of the API contract.
+#[derive(Debug, thiserror::Error)]
+pub enum ApprovalError {
+    #[error("Approval {0} not found")]
+    NotFound(String),
+    #[error("Approval is {status}, not pending")]
+    NotPending { status: String },
+    #[error("Approval expired at {expires_at} and can no longer be executed")]
+    Expired { expires_at: i64 },
+    #[error("Requesters cannot approve their own transactions")]
+    SelfApproval,
+    #[error("Caller does not have the approver role")]
+    NotApprover,
+    #[error("The signing key of this request is missing or no longer opens (envelope key rotated); send it again")]
+    SignerUnavailable,
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+impl ApprovalError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApprovalError::NotFound(_) => "APPROVAL_NOT_FOUND",
+            ApprovalError::NotPending { .. } => "APPROVAL_NOT_PENDING",
+            ApprovalError::Expired { .. } => "APPROVAL_EXPIRED",
+            ApprovalError::SelfApproval => "SELF_APPROVAL_FORBIDDEN",
+            ApprovalError::NotApprover => "NOT_AN_APPROVER",
+            ApprovalError::SignerUnavailable => "APPROVAL_SIGNER_UNAVAILABLE",
+            ApprovalError::Storage(_) => "DB_ERROR",
+        }
+    }
+}
+
+/// A send to hold for review
+#[derive(Debug, Clone)]
+pub struct HoldRequest<'a> {
+    pub wallet_name: &'a str,
+    pub network: &'a str,
+    /// Decimal whole units
+    pub amount: &'a str,
+    pub payload: ApprovalPayload,
+    /// User id, or `admin` for API-key callers
+    pub requested_by: &'a str,
+    pub token_id: Option<&'a str>,
+}
+
+/// Outcome of the review-threshold check
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ReviewCheck {
+    /// Decimal whole units; `None` when the wallet has no threshold
+    pub threshold: Option<String>,
+    pub required: bool,
+}
+
+pub struct ApprovalQueue {
+    config: ApprovalConfig,
+    storage: Arc<WalletStorage>,
+}
+
+impl ApprovalQueue {
+    pub fn new(config: ApprovalConfig, storage: Arc<WalletStorage>) -> Self {
+        Self { config, storage }
+    }
+
+    /// Approvers are configured by user id or email.
+    pub fn is_approver(&self, user_id: &str, email: Option<&str>) -> bool {
+        self.config.approvers.iter().any(|a| a == user_id || email.is_some_and(|e| a.eq_ignore_ascii_case(e)))
+    }
+
+    /// Whether a send of `amount` (decimal whole units) from `wallet_name`
+    /// has to be approved first.
+    pub async fn needs_review(&self, wallet_name: &str, amount: &str) -> Result<bool, ApprovalError> {
+        Ok(self.review(wallet_name, amount).await?.required)
+    }
+
+    /// [`Self::needs_review`] together with the threshold it compared against.
+    pub async fn review(&self, wallet_name: &str, amount: &str) -> Result<ReviewCheck, ApprovalError> {
+        let Some(threshold) = self.storage.review_threshold(wallet_name).await? else {
+            return Ok(ReviewCheck { threshold: None, required: false });
+        };
+        // 两者都是已validate的十进制金额；无法解析时按需审批处理
+        let required = match (ethers::utils::parse_ether(amount), ethers::utils::parse_ether(&threshold)) {
+            (Ok(amount), Ok(threshold)) => amount > threshold,
+            _ => true,
+        };
+        Ok(ReviewCheck { threshold: Some(threshold), required })
+    }
+
+    /// Stores the request as `pending`, with `signer` sealed to it until it is decided.
+    pub async fn hold(&self, request: HoldRequest<'_>, signer: LocalWallet) -> Result<ApprovalRecord, ApprovalError> {
+        self.expire_overdue().await?;
+        let now = self.storage.clock().now().timestamp();
+        let record = ApprovalRecord {
+            id: self.storage.id_generator().new_id(),
+            wallet_name: request.wallet_name.to_string(),
+            network: request.network.to_string(),
+            payload: request.payload,
+            amount: request.amount.to_string(),
+            requested_by: request.requested_by.to_string(),
+            token_id: request.token_id.map(str::to_string),
+            status: APPROVAL_PENDING.to_string(),
+            decided_by: None,
+            reason: None,
+            tx_hash: None,
+            created_at: now,
+            expires_at: now.saturating_add(self.config.expiry_secs as i64),
+            decided_at: None,
+        };
+        let key = ApprovalKey::seal(&held(&record), &signer).map_err(anyhow::Error::from)?;
+        self.storage.create_approval(&record, &key).await?;
+        self.audit(&record, "approval.requested", request.requested_by, None).await;
+        info!(
+            "send of {} from {} on {} held for approval {}",
+            record.amount, record.wallet_name, record.network, record.id
+        );
+        Ok(record)
+    }
+
+    /// Newest first, after expiring overdue requests.
+    pub async fn list(&self, status: Option<&str>, limit: i64) -> Result<Vec<ApprovalRecord>, ApprovalError> {
+        self.expire_overdue().await?;
+        Ok(self.storage.list_approvals(status, limit).await?)
+    }
+
+    /// Checks that `approver` may approve `id` now. Does not change anything
+    /// except expiring (or failing, if its key no longer opens) a dead request.
+    pub async fn check(&self, id: &str, approver: &str) -> Result<ApprovalRecord, ApprovalError> {
+        let record = self.pending(id).await?;
+        if record.requested_by == approver {
+            return Err(ApprovalError::SelfApproval);
+        }
+        if let Err(e) = self.signer(&record).await {
+            warn!("approval {} cannot be executed: {}", id, e);
+            let reason = ApprovalError::SignerUnavailable.to_string();
+            self.finish(&record, APPROVAL_PENDING, APPROVAL_FAILED, approver, Some(&reason)).await?;
+            return Err(ApprovalError::SignerUnavailable);
+        }
+        Ok(record)
+    }
+
+    /// Moves a checked request to `approved` and hands out its signer. Fails
+    /// if another approver, the requester's rejection or expiry got there first.
+    pub async fn claim(&self, record: &ApprovalRecord, approver: &str) -> Result<LocalWallet, ApprovalError> {
+        // 状态迁移会删除密钥，先打开
+        let signer = self.signer(record).await.map_err(|e| {
+            warn!("approval {} cannot be executed: {}", record.id, e);
+            ApprovalError::SignerUnavailable
+        })?;
+        let decision = ApprovalDecision { decided_by: Some(approver), ..Default::default() };
+        if !self.storage.transition_approval(&record.id, APPROVAL_PENDING, APPROVAL_APPROVED, &decision).await? {
+            return Err(self.pending(&record.id).await.err().unwrap_or(ApprovalError::NotPending {
+                status: APPROVAL_APPROVED.to_string(),
+            }));
+        }
+        self.audit(record, "approval.approved", approver, None).await;
+        Ok(signer)
+    }
+
+    /// Records the broadcast of an approved send.
+    pub async fn executed(&self, record: &ApprovalRecord, tx_hash: &str) -> Result<(), ApprovalError> {
+        self.storage.set_approval_tx_hash(&record.id, tx_hash).await?;
+        let details = serde_json::json!({ "tx_hash": tx_hash });
+        self.audit(record, "approval.executed", record.decided_by.as_deref().unwrap_or_default(), Some(details))
+            .await;
+        Ok(())
+    }
+
+    /// An approved send that could not be signed or broadcast.
+    pub async fn failed(&self, record: &ApprovalRecord, approver: &str, reason: &str) -> Result<(), ApprovalError> {
+        self.finish(record, APPROVAL_APPROVED, APPROVAL_FAILED, approver, Some(reason)).await
+    }
+
+    pub async fn reject(&self, id: &str, approver: &str, reason: &str) -> Result<ApprovalRecord, ApprovalError> {
+        let record = self.pending(id).await?;
+        self.finish(&record, APPROVAL_PENDING, APPROVAL_REJECTED, approver, Some(reason)).await?;
+        self.storage.approval(id).await?.ok_or_else(|| ApprovalError::NotFound(id.to_string()))
+    }
+
+    pub async fn get(&self, id: &str) -> Result<ApprovalRecord, ApprovalError> {
+        self.storage.approval(id).await?.ok_or_else(|| ApprovalError::NotFound(id.to_string()))
+    }
+
+    /// Opens the sealed key of a pending request.
+    async fn signer(&self, record: &ApprovalRecord) -> anyhow::Result<LocalWallet> {
+        let key = self
+            .storage
+            .approval_key(&record.id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("no sealed key stored"))?;
+        Ok(key.open(&held(record))?)
+    }
+
+    /// The record if it is still `pending` and not expired (an overdue
+    /// request is marked `expired` here).
+    async fn pending(&self, id: &str) -> Result<ApprovalRecord, ApprovalError> {
+        let record = self.get(id).await?;
+        if record.status != APPROVAL_PENDING {
+            return Err(ApprovalError::NotPending { status: record.status });
+        }
+        if record.expires_at <= self.storage.clock().now().timestamp() {
+            self.expire_overdue().await?;
+            return Err(ApprovalError::Expired { expires_at: record.expires_at });
+        }
+        Ok(record)
+    }
+
+    async fn finish(
+        &self,
+        record: &ApprovalRecord,
+        from: &str,
+        to: &str,
+        decided_by: &str,
+        reason: Option<&str>,
+    ) -> Result<(), ApprovalError> {
+        let decision = ApprovalDecision { decided_by: Some(decided_by), reason, tx_hash: None };
+        if !self.storage.transition_approval(&record.id, from, to, &decision).await? {
+            let current = self.get(&record.id).await?;
+            return Err(ApprovalError::NotPending { status: current.status });
+        }
+        let details = reason.map(|r| serde_json::json!({ "reason": r }));
+        self.audit(record, &format!("approval.{}", to), decided_by, details).await;
+        Ok(())
+    }
+
+    async fn expire_overdue(&self) -> Result<(), ApprovalError> {
+        let expired = self.storage.expire_approvals().await?;
+        if expired.is_empty() {
+            return Ok(());
+        }
+        for id in &expired {
+            if let Ok(record) = self.get(id).await {
+                self.audit(&record, &format!("approval.{}", APPROVAL_EXPIRED), "system", None).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// 审计失败不影响审批流程，只记录日志
+    async fn audit(&self, record: &ApprovalRecord, action: &str, actor: &str, extra: Option<serde_json::Value>) {
+        let mut details = serde_json::json!({
+            "approval_id": record.id,
+            "network": record.network,
+            "to": record.payload.to,
+            "amount": record.amount,
+            "requested_by": record.requested_by,
+            "actor": actor,
+        });
+        if let (Some(details), Some(serde_json::Value::Object(extra))) = (details.as_object_mut(), extra) {
+            details.extend(extra);
+        }
+        if let Err(e) = self.storage.log_action(&record.wallet_name, action, &details.to_string(), None, None).await {
+            warn!("failed to audit {} for approval {}: {}", action, record.id, e);
+        }
+    }
+}
+
+/// The transaction a sealed key is bound to
+fn held(record: &ApprovalRecord) -> HeldIntent<'_> {
+    HeldIntent {
+        approval_id: &record.id,
+        wallet_name: &record.wallet_name,
+        network: &record.network,
+        from: &record.payload.from,
+        to: &record.payload.to,
+        value: &record.payload.value,
+        token: record.payload.token.as_deref(),
+    }
+}
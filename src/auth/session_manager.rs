@@ -9,6 +9,8 @@ use parking_lot::RwLock;
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 
+use crate::ops::incidents::spawn_named;
+
 /// 会话信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
@@ -237,7 +239,7 @@ impl SessionManager {
     
     /// 启动定期清理任务
     pub async fn start_cleanup_task(self: Arc<Self>) {
-        tokio::spawn(async move {
+        spawn_named("session_cleanup", async move {
             let mut interval = tokio::time::interval(Duration::from_secs(300)); // 5 minutes
             loop {
                 interval.tick().await;
@@ -52,6 +52,8 @@ fn create_mock_wallet_data() -> SecureWalletData {
             quantum_safe: true,
             multi_sig_threshold: 1,
             networks: vec!["eth".to_string(), "polygon".to_string(), "bsc".to_string()],
+            allowed_networks: None,
+            derivation_path: None,
         },
         encrypted_master_key: vec![1, 2, 3, 4],
         shamir_shares: vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]],
@@ -59,6 +61,8 @@ fn create_mock_wallet_data() -> SecureWalletData {
         nonce: vec![9, 10, 11, 12],
         schema_version: defi_hot_wallet::core::SecureWalletData::default_schema_version(),
         kek_id: None,
+        key_kind: defi_hot_wallet::core::WalletKeyKind::Hd,
+        passphrase_protected: false,
     }
 }
 
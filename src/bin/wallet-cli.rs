@@ -1,56 +1,268 @@
 use anyhow::Context;
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use std::io::IsTerminal;
-use defi_hot_wallet::cli::{Cli, Commands};
-use defi_hot_wallet::core::config::WalletConfig;
+use std::process::ExitCode;
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::raw_tx;
+use defi_hot_wallet::blockchain::gas_oracle::{GasOracle, ProviderGasOracle, STANDARD_TRANSFER_GAS};
+use defi_hot_wallet::cli::amounts::{self, Denomination, NumberLocale, SendPreview};
+use defi_hot_wallet::cli::output::{
+    AddressBookExported, AddressBookImported, BackfillResult, BalanceResult, BroadcastResult, CliError, HelpText,
+    KeystoreExported, MnemonicResult, Output, SendResult, UsageError, WalletCreated, WalletImported, WalletList,
+    WalletSummary, SEND_CANCELLED, SEND_SUBMITTED,
+};
+use defi_hot_wallet::cli::{AddressBookCommand, Cli, Commands};
+use defi_hot_wallet::core::address_book::{self, AddressBookImport};
+use defi_hot_wallet::blockchain::rpc_limits;
+use defi_hot_wallet::core::config::{BackfillConfig, BlockchainConfig, WalletConfig};
+use defi_hot_wallet::core::wallet_manager::reconcile::{ReconcileError, ReconcileMode, ReconcileRange};
+use defi_hot_wallet::core::wallet_manager::CreateWalletOptions;
 use defi_hot_wallet::core::WalletManager;
+use defi_hot_wallet::monitoring::WalletMetrics;
+use defi_hot_wallet::ops::backfill::{BackfillError, HistoryBackfill};
+use defi_hot_wallet::ops::envelope_rotation::{self, EnvelopeRotation};
+use defi_hot_wallet::ops::reconciliation::ReconciliationJob;
 use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::WalletStorage;
+use ethers::providers::{Http, Middleware, Provider};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::io::{self, Write};
+use std::io;
+use std::sync::Arc;
 use tokio::fs;
+use tokio::io::AsyncReadExt;
+use tokio_util::sync::CancellationToken;
+use zeroize::Zeroizing;
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> ExitCode {
     let cli = Cli::parse();
+    init_logging(cli.quiet);
 
+    // locale 在 run 里解析；错误输出只用到 format
+    let output = Output { format: cli.output_format(), quiet: cli.quiet, locale: NumberLocale::EN };
+    match run(cli, output).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            output.error(&e);
+            ExitCode::from(e.exit_code as u8)
+        }
+    }
+}
+
+/// 日志写 stderr，默认只留 warn（`--quiet` 时 error），`RUST_LOG` 可覆盖
+fn init_logging(quiet: bool) {
+    let default = if quiet { "error" } else { "warn" };
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(default));
+    let _ = tracing_subscriber::fmt().with_env_filter(filter).with_writer(io::stderr).try_init();
+}
+
+async fn run(cli: Cli, output: Output) -> Result<(), CliError> {
     // 从默认配置构建，然后覆盖对测试/CLI运行重要的字段
     let mut wallet_config = WalletConfig::default();
     // 对测试使用内存中的 sqlite 以避免接触磁盘
     wallet_config.storage.database_url = "sqlite::memory:".to_string();
     // 确保区块链网络映射存在（避免需要 BlockchainConfig::default）
     wallet_config.blockchain.networks = HashMap::new();
-    let wallet_manager = WalletManager::new(&wallet_config).await?;
+    let wallet_manager = WalletManager::new(&wallet_config).await.map_err(CliError::from_wallet)?;
+    let i18n = defi_hot_wallet::i18n::init_default_languages().or_usage()?;
+    let locale = NumberLocale::resolve(cli.locale.as_deref(), std::env::var("LANG").ok().as_deref(), &i18n);
+    let output = Output { locale, ..output };
 
     match cli.command {
-        Commands::Create { name, output } => {
-            wallet_manager.create_wallet(&name, "cli_default_password", false).await?;
+        Commands::Create { name, output: path } => {
+            let storage =
+                WalletStorage::new_with_url(&wallet_config.storage.database_url).await.map_err(CliError::failure)?;
+            let options = CreateWalletOptions {
+                password: "cli_default_password".to_string(),
+                quantum_safe: false,
+                networks: vec!["eth".to_string()],
+                derivation_path: None,
+                passphrase: None,
+            };
+            let statuses = wallet_manager
+                .create_wallet_full(&name, options, &storage, &ClientRegistry::new())
+                .await
+                .map_err(CliError::from_wallet)?;
+            let status = statuses.into_iter().next().context("wallet created without a network")?;
+            let mut created =
+                WalletCreated { name: name.clone(), network: status.network, address: status.address, output: None };
             tracing::info!(name = %name, "创建钱包");
-            if let Some(path) = output.as_deref() {
-                write_wallet_output_if_requested(Some(path), &()).await?;
+            if let Some(path) = path.as_deref() {
+                write_wallet_output_if_requested(Some(path), &created).await?;
                 tracing::info!(path = %path.display(), "Wallet info written to path");
+                created.output = Some(path.display().to_string());
             }
+            output.emit(&created)?;
+        }
+        Commands::ImportKeystore { name, file } => {
+            let keystore_json = Zeroizing::new(
+                fs::read_to_string(&file)
+                    .await
+                    .with_context(|| format!("read keystore {}", file.display()))
+                    .or_usage()?,
+            );
+            let keystore_password = secret_from_env("KEYSTORE_PASSWORD")?;
+            let wallet_password = secret_from_env("WALLET_PASSWORD")?;
+            let address = wallet_manager
+                .import_keystore_v3(&name, &keystore_json, &keystore_password, &wallet_password)
+                .await
+                .map_err(CliError::from_wallet)?;
+            tracing::info!(name = %name, address = %address, "导入 keystore 钱包");
+            output.emit(&WalletImported { name, address })?;
+        }
+        Commands::ExportKeystore { name, output: path } => {
+            let wallet_password = secret_from_env("WALLET_PASSWORD")?;
+            let export_password = secret_from_env("EXPORT_PASSWORD")?;
+            let keystore = wallet_manager
+                .export_keystore_v3(&name, &wallet_password, &export_password)
+                .await
+                .map_err(CliError::from_wallet)?;
+            let json = serde_json::to_string_pretty(&keystore).context("serialize keystore")?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await.ok();
+            }
+            fs::write(&path, json).await.context("write keystore to --output path")?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+            }
+            tracing::info!(name = %name, path = %path.display(), "导出 keystore");
+            output.emit(&KeystoreExported { name, path: path.display().to_string() })?;
+        }
+        #[cfg(feature = "bitcoin")]
+        Commands::BtcDescriptor { name, address_type, account } => {
+            use defi_hot_wallet::core::wallet_manager::btc_descriptor::parse_address_type;
+
+            let address_type = parse_address_type(&address_type).map_err(CliError::from_wallet)?;
+            let wallet_password = secret_from_env("WALLET_PASSWORD")?;
+            let export = wallet_manager
+                .export_bitcoin_descriptor(&name, &wallet_password, address_type, account)
+                .await
+                .map_err(CliError::from_wallet)?;
+            tracing::info!(name = %name, fingerprint = %export.fingerprint, "导出 Bitcoin descriptor");
+            output.emit(&export)?;
+        }
+        #[cfg(not(feature = "bitcoin"))]
+        Commands::BtcDescriptor { .. } => {
+            return Err(CliError::failure(anyhow::anyhow!("built without the bitcoin feature")));
         }
         Commands::List => {
-            tracing::info!("列出所有钱包");
+            let wallets = wallet_manager.list_wallets().await.map_err(CliError::from_wallet)?;
+            output.emit(&WalletList { wallets: wallets.into_iter().map(WalletSummary::from).collect() })?;
         }
         Commands::Info { name } => {
-            tracing::info!(name = %name, "显示钱包信息");
+            let wallets = wallet_manager.list_wallets().await.map_err(CliError::from_wallet)?;
+            let info = wallets
+                .into_iter()
+                .find(|w| w.name == name)
+                .ok_or_else(|| CliError::failure(anyhow::anyhow!("wallet '{}' not found", name)))?;
+            output.emit(&WalletSummary::from(info))?;
         }
         Commands::Transfer { name, to, amount } => {
+            let network = "eth";
+            let denom = Denomination::ETH;
+            let amount = amounts::parse_amount(&amount, &denom).or_usage()?;
+            // 缺Password属于配置错误，在询价前就失败
+            let password = secret_from_env("WALLET_PASSWORD")?;
+            let gas_oracle = ProviderGasOracle::from_config(&wallet_config.blockchain);
+            let fee = transfer_fee(&gas_oracle, network).await?;
+            let preview = SendPreview { wallet: &name, network, to: &to, amount, fee };
+            let mut result = SendResult {
+                wallet: name.clone(),
+                network: network.to_string(),
+                to: to.clone(),
+                amount: amount.to_string(),
+                fee: fee.to_string(),
+                status: SEND_CANCELLED.to_string(),
+                tx_hash: None,
+            };
+            if !confirm(&output, &amounts::send_prompt(&preview, &denom, &locale), cli.yes)? {
+                tracing::info!(from = %name, "转账已取消");
+                output.emit(&result)?;
+                return Ok(());
+            }
+            let tx_hash = wallet_manager
+                .send_transaction(&name, &to, &amounts::to_decimal(amount, &denom), network, &password)
+                .await
+                .map_err(CliError::from_wallet)?;
             tracing::info!(from = %name, to = %to, amount = %amount, "转账");
+            result.status = SEND_SUBMITTED.to_string();
+            result.tx_hash = Some(tx_hash);
+            output.emit(&result)?;
         }
-        Commands::Balance { name, network: _ } => {
+        Commands::Sweep { name, to, network } => {
+            let denom = Denomination::for_network(&network)
+                .filter(|d| d.minimal_unit == "wei")
+                .ok_or_else(|| CliError::usage(anyhow::anyhow!("sweep is not supported on {}", network)))?;
+            let password = secret_from_env("WALLET_PASSWORD")?;
+            let balance =
+                wallet_manager.get_balance(&name, &network, &password).await.map_err(CliError::from_wallet)?;
+            let balance = amounts::from_decimal(&balance, &denom).map_err(CliError::failure)?;
+            let gas_oracle = ProviderGasOracle::from_config(&wallet_config.blockchain);
+            let fee = transfer_fee(&gas_oracle, &network).await?;
+            let prompt = amounts::sweep_prompt(&name, &network, &to, balance, fee, &denom, &locale)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "balance {} does not cover the fee {}",
+                        amounts::format_amount(balance, &denom, &locale),
+                        amounts::format_amount(fee, &denom, &locale)
+                    )
+                })?;
+            let mut result = SendResult {
+                wallet: name.clone(),
+                network: network.clone(),
+                to: to.clone(),
+                amount: (balance - fee).to_string(),
+                fee: fee.to_string(),
+                status: SEND_CANCELLED.to_string(),
+                tx_hash: None,
+            };
+            if !confirm(&output, &prompt, cli.yes)? {
+                tracing::info!(name = %name, "清空已取消");
+                output.emit(&result)?;
+                return Ok(());
+            }
+            let amount = amounts::to_decimal(balance - fee, &denom);
+            let tx_hash = wallet_manager
+                .send_transaction(&name, &to, &amount, &network, &password)
+                .await
+                .map_err(CliError::from_wallet)?;
+            tracing::info!(name = %name, to = %to, amount = %amount, "清空钱包");
+            result.status = SEND_SUBMITTED.to_string();
+            result.tx_hash = Some(tx_hash);
+            output.emit(&result)?;
+        }
+        Commands::Balance { name, network } => {
+            let network = network.unwrap_or_else(|| "eth".to_string());
+            let denom = Denomination::for_network(&network)
+                .ok_or_else(|| CliError::usage(anyhow::anyhow!("unsupported network: {}", network)))?;
+            let password = secret_from_env("WALLET_PASSWORD")?;
+            let balance =
+                wallet_manager.get_balance(&name, &network, &password).await.map_err(CliError::from_wallet)?;
+            let minimal = amounts::from_decimal(&balance, &denom).map_err(CliError::failure)?;
             tracing::info!(name = %name, "查询余额");
+            output.emit(&BalanceResult {
+                wallet: name,
+                network,
+                amount: minimal.to_string(),
+                decimal: amounts::to_decimal(minimal, &denom),
+                symbol: denom.symbol.to_string(),
+            })?;
         }
         Commands::Bridge { name, from_chain: _, to_chain: _, token: _, amount: _ } => {
             tracing::info!(name = %name, "桥接");
+            return Err(CliError::failure(anyhow::anyhow!("bridging is only available through the API server")));
         }
         Commands::GenerateMnemonic => {
             // simple 12-word mock mnemonic for tests
             let mnemonic_literal = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
             // keep mnemonic bytes in a zeroizing buffer to reduce plaintext lifetime
             let mnemonic = SecretVec::new(mnemonic_literal.as_bytes().to_vec());
+            let word_count = mnemonic_literal.split_whitespace().count();
+            let hidden = MnemonicResult { word_count, mnemonic: None, export_path: None };
 
             // Safer options for exporting the mnemonic:
             // 1) Require an explicit two-step opt-in: set both
@@ -65,6 +277,13 @@ async fn main() -> anyhow::Result<()> {
                 std::env::var("ALLOW_PLAINTEXT_MNEMONIC").ok().as_deref() == Some("1");
             let confirm_mnemonic =
                 std::env::var("ALLOW_PLAINTEXT_MNEMONIC_CONFIRM").ok().as_deref() == Some("1");
+            let shown = || MnemonicResult {
+                word_count,
+                mnemonic: Some(
+                    std::str::from_utf8(mnemonic.as_slice()).unwrap_or("<invalid-utf8-mnemonic>").to_string(),
+                ),
+                export_path: None,
+            };
 
             if allow_mnemonic && confirm_mnemonic {
                 // Allow tests to bypass interactive TTY checks and the prompt by setting the WALLET_TEST_CONSTRUCTOR marker.
@@ -72,11 +291,7 @@ async fn main() -> anyhow::Result<()> {
                     std::env::var("WALLET_TEST_CONSTRUCTOR").ok().as_deref() == Some("1");
                 if test_ctor {
                     // In test harnesses, print directly and avoid prompting for interactive input.
-                    if let Ok(s) = std::str::from_utf8(mnemonic.as_slice()) {
-                        println!("{}", s);
-                    } else {
-                        println!("<invalid-utf8-mnemonic>");
-                    }
+                    output.emit(&shown())?;
                     tracing::info!(
                         mnemonic = "<shown>",
                         "Generated mnemonic displayed to stdout (test constructor bypass)"
@@ -86,32 +301,26 @@ async fn main() -> anyhow::Result<()> {
 
                 // Require both stdin and stdout to be a TTY for interactive confirmation.
                 if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
-                    tracing::error!("Refusing to print mnemonic: interactive TTY required for plaintext display. Use MNEMONIC_EXPORT_KEY to write an encrypted export instead.");
-                    return Ok(());
+                    return Err(CliError::failure(anyhow::anyhow!(
+                        "refusing to print mnemonic: interactive TTY required for plaintext display. Use MNEMONIC_EXPORT_KEY to write an encrypted export instead"
+                    )));
                 }
 
                 // Prompt the operator to type a deliberate confirmation phrase to avoid accidental exposure.
-                print!("WARNING: You are about to display a secret mnemonic in plaintext. Type SHOW to confirm: ");
-                // Ensure the prompt is flushed to the terminal.
-                let _ = io::stdout().flush();
+                output.prompt("WARNING: You are about to display a secret mnemonic in plaintext. Type SHOW to confirm: ")?;
                 let mut input = String::new();
-                if let Err(e) = io::stdin().read_line(&mut input) {
-                    tracing::error!(error = %e, "Failed to read confirmation input; aborting plaintext mnemonic display");
-                    return Ok(());
-                }
+                io::stdin()
+                    .read_line(&mut input)
+                    .context("failed to read confirmation input; aborting plaintext mnemonic display")?;
                 if input.trim() != "SHOW" {
                     tracing::info!(entered = %input.trim(), "Plaintext mnemonic display aborted by operator");
+                    output.emit(&hidden)?;
                     return Ok(());
                 }
 
                 // Intentionally print the mnemonic to stdout when explicitly double-confirmed and operator typed SHOW.
-                if let Ok(s) = std::str::from_utf8(mnemonic.as_slice()) {
-                    println!("{}", s);
-                    tracing::info!(mnemonic = "<shown>", "Generated mnemonic displayed to stdout (double-confirmed + interactive confirmation)");
-                } else {
-                    println!("<invalid-utf8-mnemonic>");
-                    tracing::error!("Generated mnemonic is not valid UTF-8");
-                }
+                output.emit(&shown())?;
+                tracing::info!(mnemonic = "<shown>", "Generated mnemonic displayed to stdout (double-confirmed + interactive confirmation)");
                 return Ok(());
             }
 
@@ -128,30 +337,29 @@ async fn main() -> anyhow::Result<()> {
                 }
                 // Validate length (64 hex chars -> 32 bytes) and decode
                 if key_hex.len() != 64 {
-                    return Err(anyhow::anyhow!(
+                    return Err(CliError::usage(anyhow::anyhow!(
                         "MNEMONIC_EXPORT_KEY must be 64 hex chars (32 bytes)"
-                    ));
+                    )));
                 }
 
                 let key_bytes_vec = match hex::decode(&key_hex) {
                     Ok(b) => b,
                     Err(e) => {
-                        return Err(anyhow::anyhow!(
+                        return Err(CliError::usage(anyhow::anyhow!(
                             "MNEMONIC_EXPORT_KEY contains invalid hex: {}",
                             e
-                        ))
+                        )))
                     }
                 };
 
                 if key_bytes_vec.len() != 32 {
-                    return Err(anyhow::anyhow!(
+                    return Err(CliError::usage(anyhow::anyhow!(
                         "MNEMONIC_EXPORT_KEY decoded length is not 32 bytes"
-                    ));
+                    )));
                 }
 
                 // Zeroize the decoded key_bytes immediately and use it for encryption
                 use defi_hot_wallet::security::mnemonic_export;
-                use zeroize::Zeroizing;
 
                 let key_bytes = Zeroizing::new(key_bytes_vec);
                 let aad = out_path.as_bytes();
@@ -188,27 +396,216 @@ async fn main() -> anyhow::Result<()> {
                     tracing::warn!(path = %out_path, "Encrypted mnemonic exported; could not enforce POSIX 0o600 permissions on this platform. Secure the file manually.");
                 }
 
-                tracing::warn!(
+                tracing::info!(
                     "Encrypted mnemonic exported to {} (MNEMONIC_EXPORT_KEY used)",
                     out_path
                 );
-
+                output.emit(&MnemonicResult { export_path: Some(out_path), ..hidden })?;
                 return Ok(());
             }
 
-            // Default behavior: do not reveal mnemonic in plaintext. Log a hint to operator.
-            tracing::info!(mnemonic = "<hidden>", "Mnemonic generated. To export in plaintext set ALLOW_PLAINTEXT_MNEMONIC=1 and ALLOW_PLAINTEXT_MNEMONIC_CONFIRM=1, or provide MNEMONIC_EXPORT_KEY to write an encrypted file.");
+            // Default behavior: do not reveal mnemonic in plaintext. Tell the operator how to export it.
+            output.note("Mnemonic generated. To export in plaintext set ALLOW_PLAINTEXT_MNEMONIC=1 and ALLOW_PLAINTEXT_MNEMONIC_CONFIRM=1, or provide MNEMONIC_EXPORT_KEY to write an encrypted file.");
             // Also emit an info-level log that does not contain the secret
             tracing::info!(mnemonic = %if allow_mnemonic { "<shown>" } else { "<hidden>" }, "mnemonic command executed");
+            output.emit(&hidden)?;
+        }
+        Commands::DecodeTx { raw, file } => {
+            let raw = match (raw, file) {
+                (Some(raw), _) => raw,
+                (None, Some(file)) => read_raw_tx(&file).await.or_usage()?,
+                (None, None) => return Err(CliError::usage(anyhow::anyhow!("pass --raw or --file"))),
+            };
+            let tx = raw_tx::decode_raw_hex(&raw).or_usage()?;
+            output.emit(&tx)?;
+        }
+        Commands::Broadcast { raw_tx_file, network, allow_unprotected } => {
+            // CLI 清空了 wallet_config 的网络表，chain id 与 RPC 取内置网络配置
+            let networks = BlockchainConfig::default();
+            let policy = raw_tx::RawTxPolicy::for_network(&networks, &network, allow_unprotected).or_usage()?;
+            let raw = read_raw_tx(&raw_tx_file).await.or_usage()?;
+            let tx = raw_tx::decode_raw_hex(&raw).or_usage()?;
+            policy.check(&tx).or_usage()?;
+            match std::env::var("DATABASE_URL") {
+                Ok(url) => {
+                    let storage = WalletStorage::new_with_url(&url).await.or_usage()?;
+                    raw_tx::check_intent_nonce(&storage, &network, &tx).await.map_err(CliError::failure)?;
+                }
+                Err(_) => tracing::warn!("DATABASE_URL not set; signing intent nonce check skipped"),
+            }
+            let rpc_url = &networks.networks[&network].rpc_url;
+            let provider = Provider::<Http>::try_from(rpc_url.as_str()).context("connect to RPC").or_usage()?;
+            let bytes = hex::decode(raw.trim().trim_start_matches("0x")).context("decode raw transaction").or_usage()?;
+            let pending = provider.send_raw_transaction(bytes.into()).await.context("broadcast transaction")?;
+            tracing::info!(network = %network, from = ?tx.from, nonce = %tx.nonce, "广播外部签名交易");
+            output.emit(&BroadcastResult {
+                network,
+                tx_hash: format!("{:?}", pending.tx_hash()),
+                from: format!("{:?}", tx.from),
+                nonce: tx.nonce.to_string(),
+            })?;
+        }
+        Commands::AddressBook(AddressBookCommand::Import { wallet, file, dry_run, strict }) => {
+            let storage = address_book_storage(&wallet).await?;
+            let mut import = AddressBookImport::new(storage.address_book(&wallet).await?);
+            let mut reader = fs::File::open(&file).await.with_context(|| format!("read {}", file.display())).or_usage()?;
+            let mut chunk = vec![0u8; 64 * 1024];
+            loop {
+                let n = reader.read(&mut chunk).await.with_context(|| format!("read {}", file.display()))?;
+                if n == 0 {
+                    break;
+                }
+                import.feed(&chunk[..n]).or_usage()?;
+            }
+            let plan = import.finish().or_usage()?;
+
+            let rejected = strict && !plan.invalid.is_empty();
+            let applied = !dry_run && !rejected && !plan.is_noop();
+            let counts = plan.counts();
+            if applied {
+                storage.upsert_address_book(&wallet, &plan.changes()).await?;
+                let details = serde_json::json!({ "applied": true, "strict": strict, "counts": counts, "source": "cli" });
+                storage.log_action(&wallet, "address_book_import", &details.to_string(), None, None).await?;
+            }
+            output.emit(&AddressBookImported { wallet, dry_run, strict, applied, counts, plan })?;
+            if rejected {
+                return Err(CliError::failure(anyhow::anyhow!("{} invalid rows; nothing written", counts.invalid)));
+            }
+        }
+        Commands::AddressBook(AddressBookCommand::Export { wallet, output: path }) => {
+            let storage = address_book_storage(&wallet).await?;
+            let entries = storage.address_book(&wallet).await?;
+            let csv = address_book::export_csv(&entries);
+            let result = match path {
+                Some(path) => {
+                    fs::write(&path, csv).await.context("write address book to --output path")?;
+                    AddressBookExported {
+                        wallet,
+                        entries: entries.len(),
+                        path: Some(path.display().to_string()),
+                        csv: None,
+                    }
+                }
+                None => AddressBookExported { wallet, entries: entries.len(), path: None, csv: Some(csv) },
+            };
+            output.emit(&result)?;
+        }
+        Commands::Backfill { wallet, network, from_block } => {
+            let url = std::env::var("DATABASE_URL")
+                .map_err(|_| CliError::usage(anyhow::anyhow!("DATABASE_URL must be set")))?;
+            let networks = BlockchainConfig::default();
+            if !networks.networks.contains_key(&network) {
+                return Err(CliError::usage(anyhow::anyhow!("Unsupported network: {}", network)));
+            }
+            let storage = Arc::new(WalletStorage::new_with_url(&url).await.or_usage()?);
+            let metrics = Arc::new(WalletMetrics::new()?);
+            let clients = Arc::new(ClientRegistry::from_config_with_metrics(&networks, metrics.clone()));
+            let backfill = HistoryBackfill::from_config(&BackfillConfig::default(), storage.clone(), clients, metrics);
+            let backfill_error = |e: BackfillError| match e {
+                BackfillError::Chain(e) => CliError::from_wallet(e),
+                other => CliError::failure(other),
+            };
+            backfill.request(&wallet, &network, from_block).await.map_err(backfill_error)?;
+            // 后台身份：限流时排队等待，不因用户等待预算而失败
+            let cancel = CancellationToken::new();
+            let run = backfill.run_to_completion(&wallet, &network, cancel.clone());
+            let cursor = rpc_limits::background(cancel, run).await.map_err(backfill_error)?;
+            let balances = storage.ledger_balances(&wallet, &network).await?;
+            tracing::info!(wallet = %wallet, network = %network, entries = cursor.entries, "回填链上历史");
+            output.emit(&BackfillResult { wallet, network, cursor, balances })?;
+        }
+        Commands::Reconcile { from, to, networks, apply } => {
+            let url = std::env::var("DATABASE_URL")
+                .map_err(|_| CliError::usage(anyhow::anyhow!("DATABASE_URL must be set")))?;
+            let config = WalletConfig { blockchain: BlockchainConfig::default(), ..wallet_config.clone() };
+            let storage = Arc::new(WalletStorage::new_with_url(&url).await.or_usage()?);
+            let metrics = Arc::new(WalletMetrics::new()?);
+            let clients = Arc::new(ClientRegistry::from_config_with_metrics(&config.blockchain, metrics.clone()));
+            let backfill = HistoryBackfill::from_config(&config.backfill, storage.clone(), clients.clone(), metrics);
+            let manager = Arc::new(WalletManager::new(&config).await.map_err(CliError::from_wallet)?);
+            let job = ReconciliationJob::new(manager, storage, Arc::new(backfill), clients);
+            let mode = if apply { ReconcileMode::Apply } else { ReconcileMode::Report };
+            // 后台身份：限流时排队等待，不因user等待预算而失败
+            let cancel = CancellationToken::new();
+            let run = job.run_direct(ReconcileRange { from, to }, &networks, mode, "cli", &cancel);
+            let report = rpc_limits::background(cancel.clone(), run).await.map_err(|e| match e {
+                ReconcileError::UnknownNetwork(_) | ReconcileError::InvalidRange(..) => CliError::usage(e),
+                ReconcileError::Chain(e) => CliError::from_wallet(e),
+                other => CliError::failure(other),
+            })?;
+            tracing::info!(discrepancies = report.totals.discrepancies, applied = report.totals.applied, "链上对账");
+            output.emit(&report)?;
+        }
+        Commands::RotateKek { to, kek_id, dry_run } => {
+            let url = std::env::var("DATABASE_URL")
+                .map_err(|_| CliError::usage(anyhow::anyhow!("DATABASE_URL must be set")))?;
+            let old_kek = envelope_rotation::decode_kek(&secret_from_env("WALLET_ENC_KEY")?).or_usage()?;
+            let new_kek = envelope_rotation::decode_kek(&secret_from_env("NEW_WALLET_ENC_KEY")?).or_usage()?;
+            let storage = WalletStorage::new_with_url(&url).await.or_usage()?;
+            let mut rotation = EnvelopeRotation::new(old_kek, new_kek);
+            if let Some(kek_id) = kek_id {
+                rotation = rotation.with_kek_id(kek_id);
+            }
+            if let Some(mode) = to {
+                rotation = rotation.with_target(mode);
+            }
+            let report = rotation.run(&storage, dry_run).await.map_err(CliError::from_wallet)?;
+            tracing::info!(kek_id = %report.kek_id, dry_run, applied = report.applied, "轮换 KEK");
+            output.emit(&report)?;
+            if report.totals.failed > 0 {
+                let failed = report.totals.failed;
+                return Err(CliError::failure(anyhow::anyhow!("{} wallets failed; nothing written", failed)));
+            }
         }
         Commands::Help => {
-            tracing::info!("Help requested");
+            output.emit(&HelpText { usage: Cli::command().render_help().to_string() })?;
         }
     }
 
     Ok(())
 }
 
+/// 普通转账的手续费（wei）：当前 gas price × 21000
+async fn transfer_fee(gas_oracle: &impl GasOracle, network: &str) -> anyhow::Result<u128> {
+    let gas_price = gas_oracle.gas_price(network).await?;
+    Ok((gas_price * STANDARD_TRANSFER_GAS).as_u128())
+}
+
+/// 在 stderr 上打印确认提示并读取 y/N；`--yes` 时直接通过
+fn confirm(output: &Output, prompt: &str, assume_yes: bool) -> anyhow::Result<bool> {
+    if assume_yes {
+        output.note(format!("{}y (--yes)", prompt));
+        return Ok(true);
+    }
+    output.prompt(prompt)?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(matches!(input.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
+
+/// DATABASE_URL 指向的数据库；wallet不在其中时退出码 1
+async fn address_book_storage(wallet: &str) -> Result<WalletStorage, CliError> {
+    let url = std::env::var("DATABASE_URL").map_err(|_| CliError::usage(anyhow::anyhow!("DATABASE_URL must be set")))?;
+    let storage = WalletStorage::new_with_url(&url).await.or_usage()?;
+    if !storage.list_wallets().await?.iter().any(|w| w.name == wallet) {
+        return Err(CliError::failure(anyhow::anyhow!("Wallet not found: {}", wallet)));
+    }
+    Ok(storage)
+}
+
+/// 文件中的原始交易 hex（去掉首尾空白）
+async fn read_raw_tx(path: &std::path::Path) -> anyhow::Result<String> {
+    let raw = fs::read_to_string(path).await.with_context(|| format!("read {}", path.display()))?;
+    Ok(raw.trim().to_string())
+}
+
+/// 从环境变量读取Password，避免出现在命令行参数和 shell 历史中；缺失时退出码 2。
+fn secret_from_env(var: &str) -> Result<Zeroizing<String>, CliError> {
+    std::env::var(var)
+        .map(Zeroizing::new)
+        .map_err(|_| CliError::usage(anyhow::anyhow!("{} must be set", var)))
+}
+
 /// 辅助函数：如果提供了 --output 路径，则将钱包信息写入文件。
 async fn write_wallet_output_if_requested(
     output_path: Option<&std::path::Path>,
@@ -415,11 +415,8 @@ mod tests {
     #[test]
     fn test_secret_key_not_zero() {
         let keypair = BitcoinKeypair::generate(Network::Bitcoin).unwrap();
-        let _secret = keypair.secret_key();
-        
         // 密钥不应该全为零
-        // SecretKey 没有直接的 iter 方法，简化测试
-        assert!(true, "密钥已生成");
+        assert_ne!(keypair.secret_key().secret_bytes(), [0u8; 32]);
     }
     
     #[test]
@@ -5,7 +5,7 @@
 use super::account::BitcoinKeypair;
 use super::address::{AddressType, BitcoinAddress};
 use super::transaction::BitcoinTransaction;
-use super::utxo::{SelectionStrategy, Utxo, UtxoSelector};
+use super::utxo::Utxo;
 use crate::blockchain::traits::{BlockchainClient, TransactionStatus};
 use crate::core::domain::PrivateKey;
 use crate::core::errors::WalletError;
@@ -218,28 +218,20 @@ impl BitcoinClient {
         // 3. 估算手续费率
         let fee_rate = self.estimate_fee_rate(6).await?;
         
-        // 4. 选择 UTXO
-        let (selected_utxos, estimated_fee) = UtxoSelector::select(
-            &utxos,
-            amount,
-            fee_rate,
-            SelectionStrategy::BestFit,
-        )?;
-        
-        info!("选择了 {} 个 UTXO，预估手续费: {} sat", selected_utxos.len() as u32, estimated_fee);
-        
-        // 5. 构建transaction
-        let tx = BitcoinTransaction::build(
+        // 4. 按费率选择 UTXO 并构建transaction
+        let (tx, fee) = BitcoinTransaction::build_with_fee_rate(
             keypair,
-            &selected_utxos,
+            &utxos,
             to_address,
             amount,
-            estimated_fee,
+            fee_rate,
             address_type,
             self.network,
         )?;
         
-        // 6. 序列化并广播
+        info!("使用了 {} 个 UTXO，手续费: {} sat", tx.input.len(), fee);
+        
+        // 5. 序列化并广播
         let tx_hex = BitcoinTransaction::serialize(&tx);
         let txid = self.send_raw_transaction(&tx_hex).await?;
         
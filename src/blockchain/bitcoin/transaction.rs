@@ -4,7 +4,7 @@
 
 use super::account::BitcoinKeypair;
 use super::address::AddressType;
-use super::utxo::Utxo;
+use super::utxo::{SelectionStrategy, Utxo, UtxoSelector};
 use crate::core::errors::WalletError;
 use bitcoin::absolute::LockTime;
 use bitcoin::address::Address;
@@ -18,6 +18,42 @@ use bitcoin::transaction::Version;
 use std::str::FromStr;
 use tracing::{debug, info};
 
+/// 灰尘阈值：低于此金额的找零不输出，并入手续费
+pub const DUST_THRESHOLD: u64 = 546;
+
+/// 按费率构建时选择 UTXO 的策略
+const FEE_RATE_SELECTION: SelectionStrategy = SelectionStrategy::BestFit;
+
+/// 各类输入的 weight（WU），按最长的sign估算，实际transaction只会略小
+///
+/// - Legacy：outpoint 36 + scriptSig 长度 1 + scriptSig 108（73 字节 DER sign、33 字节公钥）+ sequence 4
+/// - SegWit：41 字节非见证部分 + 见证 109（项数、73 字节sign、33 字节公钥）
+/// - Taproot：41 字节非见证部分 + 见证 66（项数、64 字节 Schnorr sign）
+fn input_weight(address_type: AddressType) -> u64 {
+    match address_type {
+        AddressType::Legacy => 149 * 4,
+        AddressType::SegWit => 41 * 4 + 109,
+        AddressType::Taproot => 41 * 4 + 66,
+    }
+}
+
+/// 找零输出（发回发送者同类address）的脚本长度
+fn change_script_len(address_type: AddressType) -> usize {
+    match address_type {
+        AddressType::Legacy => 25,
+        AddressType::SegWit => 22,
+        AddressType::Taproot => 34,
+    }
+}
+
+fn varint_len(n: usize) -> u64 {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        _ => 5,
+    }
+}
+
 /// Bitcoin transaction构建器
 pub struct BitcoinTransaction;
 
@@ -87,7 +123,6 @@ impl BitcoinTransaction {
         ];
         
         // 找零输出（如果有且大于灰尘阈值 546 sat）
-        const DUST_THRESHOLD: u64 = 546;
         if change_amount >= DUST_THRESHOLD {
             let change_address = super::address::BitcoinAddress::from_public_key_legacy(
                 keypair.public_key(),
@@ -216,7 +251,6 @@ impl BitcoinTransaction {
         ];
         
         // 找零输出（如果有且大于灰尘阈值 546 sat）
-        const DUST_THRESHOLD: u64 = 546;
         if change_amount >= DUST_THRESHOLD {
             let change_address = super::address::BitcoinAddress::from_public_key_segwit(
                 keypair.public_key(),
@@ -363,7 +397,6 @@ impl BitcoinTransaction {
         ];
         
         // 找零输出（如果有且大于灰尘阈值 546 sat）
-        const DUST_THRESHOLD: u64 = 546;
         if change_amount >= DUST_THRESHOLD {
             let change_address = super::address::BitcoinAddress::from_public_key_taproot(
                 keypair.public_key(),
@@ -455,6 +488,111 @@ impl BitcoinTransaction {
         }
     }
     
+    /// 估算transaction的虚拟大小（vbytes）
+    ///
+    /// `address_type` 决定每个输入的大小；`output_script_lens` 为各输出脚本的长度。
+    /// 按最长sign估算，sign后的实际大小不会超过估算值。
+    pub fn estimate_vsize(address_type: AddressType, input_count: usize, output_script_lens: &[usize]) -> u64 {
+        // version 4 + locktime 4 + 输入、输出个数
+        let mut weight = (8 + varint_len(input_count) + varint_len(output_script_lens.len())) * 4;
+        if address_type != AddressType::Legacy {
+            // segwit marker 与 flag
+            weight += 2;
+        }
+        weight += input_weight(address_type) * input_count as u64;
+        for &len in output_script_lens {
+            weight += (8 + varint_len(len) + len as u64) * 4;
+        }
+        weight.div_ceil(4)
+    }
+
+    /// 按费率（sat/vbyte）构建transaction，自动选择 UTXO
+    ///
+    /// 用 [`UtxoSelector`] 选出覆盖金额与手续费的 UTXO，按选中的输入个数和输出
+    /// 重新估算手续费，直到选择不再变化。找零低于 [`DUST_THRESHOLD`] 时不输出，
+    /// 并入手续费。返回transaction与实际支付的手续费（sat）。
+    pub fn build_with_fee_rate(
+        keypair: &BitcoinKeypair,
+        utxos: &[Utxo],
+        to_address: &str,
+        amount: u64,
+        fee_rate_sat_per_vb: u64,
+        address_type: AddressType,
+        network: Network,
+    ) -> Result<(Transaction, u64), WalletError> {
+        info!("按费率构建transaction: 金额={} sat, 费率={} sat/vbyte", amount, fee_rate_sat_per_vb);
+
+        if amount == 0 {
+            return Err(WalletError::ValidationError("transaction金额不能为零".to_string()));
+        }
+        if fee_rate_sat_per_vb == 0 {
+            return Err(WalletError::ValidationError("费率必须大于零".to_string()));
+        }
+        let recipient_script_len = Address::from_str(to_address)
+            .map_err(|e| WalletError::InvalidAddress(format!("无效的address: {}", e)))?
+            .require_network(network)
+            .map_err(|e| WalletError::InvalidAddress(format!("addressnetwork不匹配: {}", e)))?
+            .script_pubkey()
+            .len();
+
+        let fee_for = |input_count: usize, change: bool| -> u64 {
+            let mut outputs = vec![recipient_script_len];
+            if change {
+                outputs.push(change_script_len(address_type));
+            }
+            Self::estimate_vsize(address_type, input_count, &outputs).saturating_mul(fee_rate_sat_per_vb)
+        };
+        // 选中的 UTXO 够用时返回传给 build 的手续费：有找零时按两个输出计费，
+        // 否则剩余全部作为手续费（build 不会再加找零输出）
+        let settle = |selected: &[Utxo]| -> Option<u64> {
+            let total: u64 = selected.iter().map(|u| u.amount).sum();
+            let with_change = fee_for(selected.len(), true);
+            if total >= amount.saturating_add(with_change).saturating_add(DUST_THRESHOLD) {
+                return Some(with_change);
+            }
+            if total >= amount.saturating_add(fee_for(selected.len(), false)) {
+                Some(total - amount)
+            } else {
+                None
+            }
+        };
+
+        // 每轮的手续费估算只会变大，最多 utxos.len() 轮就稳定
+        let mut fee_guess = fee_for(1, true);
+        let (selected, fee) = loop {
+            let target = amount.saturating_add(fee_guess);
+            let selected = match UtxoSelector::select(utxos, target, 0, FEE_RATE_SELECTION) {
+                Ok((selected, _)) => selected,
+                // 带找零凑不够时，全部 UTXO 不找零可能仍然够
+                Err(WalletError::InsufficientFunds(_)) if settle(utxos).is_some() => utxos.to_vec(),
+                Err(WalletError::InsufficientFunds(_)) => {
+                    let available: u64 = utxos.iter().map(|u| u.amount).sum();
+                    let required = amount.saturating_add(fee_for(utxos.len().max(1), false));
+                    return Err(WalletError::InsufficientFunds(format!(
+                        "balance不足: 需要 {} sat（含手续费）, 可用 {} sat, 缺口 {} sat",
+                        required,
+                        available,
+                        required.saturating_sub(available)
+                    )));
+                }
+                Err(e) => return Err(e),
+            };
+            if let Some(fee) = settle(&selected) {
+                break (selected, fee);
+            }
+            let needed = fee_for(selected.len(), true);
+            debug!("{} 个输入需要手续费 {} sat，重新选择", selected.len(), needed);
+            fee_guess = needed.max(fee_guess.saturating_add(1));
+        };
+
+        let tx = Self::build(keypair, &selected, to_address, amount, fee, address_type, network)?;
+        let total: u64 = selected.iter().map(|u| u.amount).sum();
+        let change: u64 = tx.output.iter().skip(1).map(|o| o.value.to_sat()).sum();
+        let fee_paid = total - amount - change;
+        info!("✅ 选择了 {} 个 UTXO，实际手续费={} sat，vsize={}", selected.len(), fee_paid, tx.vsize());
+        Ok((tx, fee_paid))
+    }
+
     /// 序列化transaction为十六进制字符串
     pub fn serialize(tx: &Transaction) -> String {
         hex::encode(serialize(tx))
@@ -1236,5 +1374,121 @@ mod tests {
         let sig_bytes = tx.input[0].witness.nth(0).unwrap();
         assert_eq!(sig_bytes.len(), 64, "Taproot 应该使用 64 字节 Schnorr sign");
     }
-}
 
+    // ============ 按费率构建 ============
+
+    fn utxo_of(n: u64, amount: u64, address_type: AddressType) -> Utxo {
+        let script_pubkey = match address_type {
+            AddressType::Legacy => "76a914".to_string() + &"00".repeat(20) + "88ac",
+            AddressType::SegWit => "0014".to_string() + &"00".repeat(20),
+            AddressType::Taproot => "5120".to_string() + &"00".repeat(32),
+        };
+        Utxo::new(format!("{:064x}", n), 0, amount, script_pubkey, 6)
+    }
+
+    fn estimated(tx: &Transaction, address_type: AddressType) -> u64 {
+        let scripts: Vec<usize> = tx.output.iter().map(|o| o.script_pubkey.len()).collect();
+        BitcoinTransaction::estimate_vsize(address_type, tx.input.len(), &scripts)
+    }
+
+    #[test]
+    fn test_fee_rate_estimate_matches_serialized_size() {
+        let keypair = BitcoinKeypair::generate(Network::Testnet).unwrap();
+        for address_type in [AddressType::Legacy, AddressType::SegWit, AddressType::Taproot] {
+            let utxos: Vec<Utxo> = (1..=3).map(|n| utxo_of(n, 40_000, address_type)).collect();
+            let to_address =
+                BitcoinAddress::from_public_key(keypair.public_key(), address_type, Network::Testnet).unwrap();
+
+            // 需要两个输入，并有找零
+            let (tx, fee) = BitcoinTransaction::build_with_fee_rate(
+                &keypair, &utxos, &to_address, 60_000, 12, address_type, Network::Testnet,
+            ).unwrap();
+            assert_eq!(tx.input.len(), 2, "{:?}", address_type);
+            assert_eq!(tx.output.len(), 2, "{:?}", address_type);
+
+            // 估算只会偏大，每个输入最多差 2 vbytes（DER sign长度不固定）
+            let estimate = estimated(&tx, address_type);
+            let actual = tx.vsize() as u64;
+            assert!(
+                estimate >= actual && estimate - actual <= 2 * tx.input.len() as u64 + 1,
+                "{:?}: estimated {} vs serialized {}", address_type, estimate, actual
+            );
+            assert_eq!(fee, estimate * 12);
+            assert!(fee >= actual * 12, "不能低于目标费率");
+
+            let change = tx.output[1].value.to_sat();
+            assert_eq!(80_000, 60_000 + change + fee);
+        }
+    }
+
+    #[test]
+    fn test_fee_rate_selection_grows_with_fee() {
+        let keypair = BitcoinKeypair::generate(Network::Testnet).unwrap();
+        let to_address = BitcoinAddress::from_public_key_segwit(keypair.public_key(), Network::Testnet).unwrap();
+        let utxos: Vec<Utxo> = (1..=4).map(|n| utxo_of(n, 10_000, AddressType::SegWit)).collect();
+
+        // 低费率两个输入够用；高费率下手续费把选择推到第三个输入
+        let (low, _) = BitcoinTransaction::build_with_fee_rate(
+            &keypair, &utxos, &to_address, 19_000, 1, AddressType::SegWit, Network::Testnet,
+        ).unwrap();
+        assert_eq!(low.input.len(), 2);
+        let (high, fee) = BitcoinTransaction::build_with_fee_rate(
+            &keypair, &utxos, &to_address, 19_000, 20, AddressType::SegWit, Network::Testnet,
+        ).unwrap();
+        assert_eq!(high.input.len(), 3);
+        assert!(fee >= high.vsize() as u64 * 20);
+    }
+
+    #[test]
+    fn test_fee_rate_dust_change_goes_to_fee() {
+        let keypair = BitcoinKeypair::generate(Network::Testnet).unwrap();
+        let to_address = BitcoinAddress::from_public_key_segwit(keypair.public_key(), Network::Testnet).unwrap();
+        let utxos = vec![utxo_of(1, 10_000, AddressType::SegWit)];
+        let with_change = BitcoinTransaction::estimate_vsize(AddressType::SegWit, 1, &[22, 22]) * 2;
+
+        // 找零只剩 300 sat（低于 546）：不输出找零，剩余全部作为手续费
+        let amount = 10_000 - with_change - 300;
+        let (tx, fee) = BitcoinTransaction::build_with_fee_rate(
+            &keypair, &utxos, &to_address, amount, 2, AddressType::SegWit, Network::Testnet,
+        ).unwrap();
+        assert_eq!(tx.output.len(), 1);
+        assert_eq!(tx.output[0].value, Amount::from_sat(amount));
+        assert_eq!(fee, 10_000 - amount);
+
+        // 找零正好等于灰尘阈值时保留
+        let amount = 10_000 - with_change - DUST_THRESHOLD;
+        let (tx, fee) = BitcoinTransaction::build_with_fee_rate(
+            &keypair, &utxos, &to_address, amount, 2, AddressType::SegWit, Network::Testnet,
+        ).unwrap();
+        assert_eq!(tx.output.len(), 2);
+        assert_eq!(tx.output[1].value, Amount::from_sat(DUST_THRESHOLD));
+        assert_eq!(fee, with_change);
+    }
+
+    #[test]
+    fn test_fee_rate_insufficient_funds_reports_shortfall() {
+        let keypair = BitcoinKeypair::generate(Network::Testnet).unwrap();
+        let to_address = BitcoinAddress::from_public_key_segwit(keypair.public_key(), Network::Testnet).unwrap();
+        let utxos: Vec<Utxo> = (1..=2).map(|n| utxo_of(n, 10_000, AddressType::SegWit)).collect();
+        let fee = BitcoinTransaction::estimate_vsize(AddressType::SegWit, 2, &[22]) * 5;
+
+        let err = BitcoinTransaction::build_with_fee_rate(
+            &keypair, &utxos, &to_address, 20_000, 5, AddressType::SegWit, Network::Testnet,
+        ).unwrap_err();
+        match err {
+            WalletError::InsufficientFunds(msg) => assert!(msg.contains(&format!("缺口 {} sat", fee)), "{}", msg),
+            other => panic!("unexpected error: {}", other),
+        }
+
+        // 全部输入、不找零正好够
+        let (tx, paid) = BitcoinTransaction::build_with_fee_rate(
+            &keypair, &utxos, &to_address, 20_000 - fee, 5, AddressType::SegWit, Network::Testnet,
+        ).unwrap();
+        assert_eq!((tx.input.len(), tx.output.len(), paid), (2, 1, fee));
+
+        let err = BitcoinTransaction::build_with_fee_rate(
+            &keypair, &utxos, &to_address, 1_000, 0, AddressType::SegWit, Network::Testnet,
+        ).unwrap_err();
+        assert!(matches!(err, WalletError::ValidationError(_)));
+    }
+}
@@ -115,7 +115,7 @@ impl UtxoSelector {
         fee_rate: u64,
     ) -> Result<(Vec<Utxo>, u64), WalletError> {
         let mut sorted_utxos = utxos.to_vec();
-        sorted_utxos.sort_by(|a, b| b.amount.cmp(&a.amount));
+        sorted_utxos.sort_by_key(|u| std::cmp::Reverse(u.amount));
         
         Self::greedy_select(&sorted_utxos, target_amount, fee_rate)
     }
@@ -127,7 +127,7 @@ impl UtxoSelector {
         fee_rate: u64,
     ) -> Result<(Vec<Utxo>, u64), WalletError> {
         let mut sorted_utxos = utxos.to_vec();
-        sorted_utxos.sort_by(|a, b| a.amount.cmp(&b.amount));
+        sorted_utxos.sort_by_key(|u| u.amount);
         
         Self::greedy_select(&sorted_utxos, target_amount, fee_rate)
     }
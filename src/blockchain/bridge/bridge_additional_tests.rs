@@ -21,6 +21,8 @@ fn create_mock_wallet_data() -> SecureWalletData {
             quantum_safe: false,
             multi_sig_threshold: 1,
             networks: vec!["eth".to_string(), "polygon".to_string()],
+            allowed_networks: None,
+            derivation_path: None,
         },
         encrypted_master_key: vec![],
         shamir_shares: vec![],
@@ -0,0 +1,159 @@
+// filepath: src/blockchain/bridge/factory.rs
+//! Route-based bridge selection.
+//!
+//! Handlers ask the factory for a `(from, to)` pair instead of constructing
+//! concrete bridges. The backend is fixed when the server starts (see
+//! `BridgeBackend::resolve`), so a production process never hands out mocks.
+//!
+//! The factory also keeps the bridges registered for route discovery; their
+//! descriptions are merged into the [`RouteMatrix`] that transfers are
+//! checked against.
+
+use std::sync::Arc;
+
+use crate::blockchain::bridge::mock::{EthereumToBSCBridge, PolygonToEthereumBridge};
+use crate::blockchain::bridge::normalize::{normalize, NormalizedTransfer};
+use crate::blockchain::bridge::routes::{BridgeSide, RouteMatrix, RouteRejection};
+use crate::blockchain::traits::Bridge;
+use crate::core::config::BridgeBackend;
+use crate::core::errors::WalletError;
+use crate::token_registry::{normalize_token, TokenRegistry};
+
+/// Route pairs the bridge layer knows how to serve.
+pub const SUPPORTED_BRIDGE_ROUTES: &[(&str, &str)] =
+    &[("eth", "bsc"), ("bsc", "eth"), ("eth", "polygon"), ("polygon", "eth")];
+
+const MOCK_ETH_BSC_CONTRACT: &str = "0xMockEthBscBridge";
+const MOCK_POLYGON_ETH_CONTRACT: &str = "0xMockPolygonBridge";
+
+#[derive(Clone)]
+pub struct BridgeFactory {
+    backend: BridgeBackend,
+    /// Bridges consulted by route discovery
+    registered: Vec<Arc<dyn Bridge>>,
+}
+
+impl std::fmt::Debug for BridgeFactory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BridgeFactory")
+            .field("backend", &self.backend)
+            .field("registered", &self.registered.len())
+            .finish()
+    }
+}
+
+impl BridgeFactory {
+    /// `backend` must already be resolved against the startup policy. The
+    /// mock backend registers its mock bridges for discovery.
+    pub fn new(backend: BridgeBackend) -> Self {
+        let registered: Vec<Arc<dyn Bridge>> = match backend {
+            BridgeBackend::Mock => vec![
+                Arc::new(EthereumToBSCBridge::new(MOCK_ETH_BSC_CONTRACT)),
+                Arc::new(PolygonToEthereumBridge::new(MOCK_POLYGON_ETH_CONTRACT)),
+            ],
+            BridgeBackend::Real => Vec::new(),
+        };
+        Self { backend, registered }
+    }
+
+    /// Registers another bridge for route discovery.
+    pub fn with_bridge(mut self, bridge: Arc<dyn Bridge>) -> Self {
+        self.registered.push(bridge);
+        self
+    }
+
+    pub fn backend(&self) -> BridgeBackend {
+        self.backend
+    }
+
+    /// Merges every registered bridge's description. A bridge whose
+    /// discovery fails is listed in `unavailable_backends` instead.
+    pub async fn route_matrix(&self, from: Option<&str>) -> RouteMatrix {
+        let mut descriptions = Vec::with_capacity(self.registered.len());
+        let mut unavailable = Vec::new();
+        for (index, bridge) in self.registered.iter().enumerate() {
+            match bridge.describe().await {
+                Ok(description) => descriptions.push(description),
+                Err(e) => {
+                    tracing::warn!("bridge route discovery failed: {:#}", e);
+                    unavailable.push(format!("bridge#{}", index));
+                }
+            }
+        }
+        let mut matrix = RouteMatrix::aggregate(&descriptions, from);
+        matrix.unavailable_backends = unavailable;
+        matrix
+    }
+
+    /// Rejects pairs, tokens and amounts no registered bridge serves, before
+    /// any backend is called, and resolves the token on both legs. Limits
+    /// are compared in the source token's minimal units.
+    pub async fn check_transfer(
+        &self,
+        tokens: &TokenRegistry,
+        from: &str,
+        to: &str,
+        token: &str,
+        amount: &str,
+    ) -> Result<NormalizedTransfer, RouteRejection> {
+        let matrix = self.route_matrix(None).await;
+        // The matrix lists assets by symbol; only the registry knows what an address is.
+        let asset = match tokens.resolve(from, token) {
+            Some(source) => source.asset,
+            None if normalize_token(token).is_ok() => {
+                return Err(RouteRejection::UnconfiguredToken {
+                    token: token.to_string(),
+                    chain: from.to_string(),
+                    side: BridgeSide::Source,
+                })
+            }
+            None => token.to_string(),
+        };
+        let entry = matrix.entry(from, to, &asset)?;
+        let transfer = normalize(tokens, from, to, token, amount)?;
+        entry.check_amount(&transfer.amount_source)?;
+        Ok(transfer)
+    }
+
+    pub fn is_supported_route(from: &str, to: &str) -> bool {
+        SUPPORTED_BRIDGE_ROUTES.iter().any(|(f, t)| *f == from && *t == to)
+    }
+
+    /// Human-readable list used in `UNSUPPORTED_BRIDGE_ROUTE` errors.
+    pub fn supported_routes_display() -> String {
+        SUPPORTED_BRIDGE_ROUTES
+            .iter()
+            .map(|(f, t)| format!("{}->{}", f, t))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Returns the bridge implementation serving `from -> to`.
+    ///
+    /// Unknown pairs yield `WalletError::ValidationError`; a supported pair on
+    /// the real backend yields `WalletError::NotImplemented` until a live
+    /// bridge integration is wired in.
+    pub fn for_route(&self, from: &str, to: &str) -> Result<Box<dyn Bridge>, WalletError> {
+        if !Self::is_supported_route(from, to) {
+            return Err(WalletError::ValidationError(format!(
+                "Unsupported bridge route {}->{}. Supported routes: {}",
+                from,
+                to,
+                Self::supported_routes_display()
+            )));
+        }
+
+        match self.backend {
+            BridgeBackend::Mock => match (from, to) {
+                ("eth", "bsc") | ("bsc", "eth") => {
+                    Ok(Box::new(EthereumToBSCBridge::new(MOCK_ETH_BSC_CONTRACT)))
+                }
+                _ => Ok(Box::new(PolygonToEthereumBridge::new(MOCK_POLYGON_ETH_CONTRACT))),
+            },
+            BridgeBackend::Real => Err(WalletError::NotImplemented(format!(
+                "No live bridge integration for route {}->{}",
+                from, to
+            ))),
+        }
+    }
+}
@@ -1,11 +1,32 @@
 // filepath: src/blockchain/bridge/mock.rs
-use crate::blockchain::bridge::relay::{mock_bridge_transfer, mock_check_transfer_status};
-use crate::blockchain::bridge::BridgeTransactionStatus;
+use crate::blockchain::bridge::relay::{mock_bridge_transfer, mock_check_transfer_status, mock_normalized_transfer};
+use crate::blockchain::bridge::routes::{BridgeDescription, BridgeRoute};
+use crate::blockchain::bridge::{BridgeTransactionStatus, NormalizedTransfer};
 use crate::blockchain::traits::Bridge;
 use crate::core::wallet_info::SecureWalletData;
 use anyhow::Result;
 use async_trait::async_trait;
 
+/// Limits advertised by the mock bridges.
+const MOCK_MIN_AMOUNT: &str = "0.000001";
+const MOCK_MAX_AMOUNT: &str = "1000000";
+const MOCK_COMPLETION_SECS: u64 = 600;
+
+/// Both directions of `a <-> b` with the same token list.
+fn mock_routes(a: &str, b: &str, tokens: &[&str]) -> Vec<BridgeRoute> {
+    [(a, b), (b, a)]
+        .into_iter()
+        .map(|(from, to)| BridgeRoute {
+            from_chain: from.to_string(),
+            to_chain: to.to_string(),
+            tokens: tokens.iter().map(|t| t.to_string()).collect(),
+            min_amount: Some(MOCK_MIN_AMOUNT.to_string()),
+            max_amount: Some(MOCK_MAX_AMOUNT.to_string()),
+            typical_completion_secs: Some(MOCK_COMPLETION_SECS),
+        })
+        .collect()
+}
+
 /// Ethereum -> BSC mock bridge.
 #[derive(Debug, Clone)]
 pub struct EthereumToBSCBridge {
@@ -39,9 +60,24 @@ impl Bridge for EthereumToBSCBridge {
         .await
     }
 
+    async fn transfer_normalized(
+        &self,
+        transfer: &NormalizedTransfer,
+        wallet_data: &SecureWalletData,
+    ) -> Result<String> {
+        mock_normalized_transfer(transfer, &self.contract_address, wallet_data).await
+    }
+
     async fn check_transfer_status(&self, tx_id: &str) -> Result<BridgeTransactionStatus> {
         mock_check_transfer_status(tx_id).await
     }
+
+    async fn describe(&self) -> Result<BridgeDescription> {
+        Ok(BridgeDescription {
+            backend: "mock-eth-bsc".to_string(),
+            routes: mock_routes("eth", "bsc", &["ETH", "BNB", "USDC", "USDT", "DAI"]),
+        })
+    }
 }
 
 /// Polygon -> Ethereum mock bridge.
@@ -77,7 +113,22 @@ impl Bridge for PolygonToEthereumBridge {
         .await
     }
 
+    async fn transfer_normalized(
+        &self,
+        transfer: &NormalizedTransfer,
+        wallet_data: &SecureWalletData,
+    ) -> Result<String> {
+        mock_normalized_transfer(transfer, &self.contract_address, wallet_data).await
+    }
+
     async fn check_transfer_status(&self, tx_id: &str) -> Result<BridgeTransactionStatus> {
         mock_check_transfer_status(tx_id).await
     }
+
+    async fn describe(&self) -> Result<BridgeDescription> {
+        Ok(BridgeDescription {
+            backend: "mock-polygon-eth".to_string(),
+            routes: mock_routes("polygon", "eth", &["ETH", "MATIC", "USDC", "USDT", "DAI"]),
+        })
+    }
 }
@@ -1,8 +1,12 @@
 // src/blockchain/bridge/mod.rs
 
 // Expose sub-modules
+pub mod factory;
+pub mod lifi;
 pub mod mock;
+pub mod normalize;
 pub mod relay;
+pub mod routes;
 pub mod transfer;
 
 use crate::core::wallet_info::SecureWalletData;
@@ -33,6 +37,28 @@ pub struct BridgeTransaction {
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub fee_amount: Option<String>,
     pub estimated_completion_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// Requested amount in the minimal units of the source / destination
+    /// token; `None` on rows written before transfers were normalized
+    #[serde(default)]
+    pub amount_minimal_source: Option<String>,
+    #[serde(default)]
+    pub amount_minimal_dest: Option<String>,
+    /// Lowercase token contracts; `None` for a native coin and on older rows
+    #[serde(default)]
+    pub token_address_source: Option<String>,
+    #[serde(default)]
+    pub token_address_dest: Option<String>,
+}
+
+impl BridgeTransaction {
+    /// Carries the resolved tokens and both minimal amounts of `transfer`.
+    pub fn with_normalized(mut self, transfer: &NormalizedTransfer) -> Self {
+        self.amount_minimal_source = Some(transfer.amount_source.minimal().to_string());
+        self.amount_minimal_dest = Some(transfer.amount_destination.minimal().to_string());
+        self.token_address_source = transfer.source.address.clone();
+        self.token_address_dest = transfer.destination.address.clone();
+        self
+    }
 }
 
 // Re-export commonly-used mock bridge implementations at the module root so
@@ -41,6 +67,11 @@ pub use mock::{
     EthereumToBSCBridge, PolygonToEthereumBridge,
 };
 
+pub use factory::{BridgeFactory, SUPPORTED_BRIDGE_ROUTES};
+pub use lifi::LiFiBridge;
+pub use normalize::NormalizedTransfer;
+pub use routes::{BridgeDescription, BridgeRoute, BridgeSide, RouteMatrix, RouteRejection};
+
 // Re-export the Bridge trait here for compatibility with existing imports
 // that expect `bridge::Bridge` to be available.
 pub use crate::blockchain::traits::Bridge;
@@ -58,6 +89,15 @@ pub async fn bridge_transfer(
         .await
 }
 
+/// Thin facade to initiate a transfer resolved by [`BridgeFactory::check_transfer`].
+pub async fn bridge_transfer_normalized(
+    bridge: &dyn Bridge,
+    transfer: &NormalizedTransfer,
+    wallet_data: &SecureWalletData,
+) -> anyhow::Result<String> {
+    transfer::initiate_normalized_transfer(bridge, transfer, wallet_data).await
+}
+
 /// Thin facade to relay/check a bridge transaction.
 pub async fn bridge_relay(
     bridge: &dyn Bridge,
@@ -65,3 +105,6 @@ pub async fn bridge_relay(
 ) -> anyhow::Result<BridgeTransactionStatus> {
     relay::relay_transaction(bridge, tx_id).await
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,98 @@
+// filepath: src/blockchain/bridge/normalize.rs
+//! Token and amount normalization for bridge transfers.
+//!
+//! A request names its token by symbol or by source-chain contract address,
+//! and its amount in whole tokens. Both legs are resolved through the
+//! [`TokenRegistry`] and must stand for the same asset. The amount is parsed
+//! once, in the source token's decimals, and rescaled to the destination's:
+//! USDC has 6 decimals on eth and 18 on bsc. An amount the destination
+//! cannot represent exactly is refused rather than rounded.
+
+use crate::blockchain::bridge::routes::{BridgeSide, RouteRejection};
+use crate::core::amount::{Amount, AmountError, AssetTag};
+use crate::token_registry::{ResolvedToken, TokenRegistry};
+
+/// A transfer request with both legs resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedTransfer {
+    pub source: ResolvedToken,
+    pub destination: ResolvedToken,
+    /// The requested amount in the source token's decimals
+    pub amount_source: Amount,
+    /// The same amount in the destination token's decimals
+    pub amount_destination: Amount,
+}
+
+impl NormalizedTransfer {
+    /// Asset symbol shared by both legs, e.g. `USDC`
+    pub fn asset(&self) -> &str {
+        &self.source.asset
+    }
+
+    /// Whole-token amount without trailing zeros, e.g. `1.5`
+    pub fn amount(&self) -> String {
+        self.amount_source.to_decimal_string()
+    }
+}
+
+/// Resolves `token` on `from` and `to` and converts `amount` into the
+/// minimal units of both.
+pub fn normalize(
+    tokens: &TokenRegistry,
+    from: &str,
+    to: &str,
+    token: &str,
+    amount: &str,
+) -> Result<NormalizedTransfer, RouteRejection> {
+    let unconfigured = |chain: &str, side| RouteRejection::UnconfiguredToken {
+        token: token.to_string(),
+        chain: chain.to_string(),
+        side,
+    };
+    let source = tokens.resolve(from, token).ok_or_else(|| unconfigured(from, BridgeSide::Source))?;
+    // An address is a source-chain contract; the destination usually has its
+    // own, found through the asset.
+    let destination = tokens
+        .resolve(to, token)
+        .or_else(|| tokens.resolve(to, &source.asset))
+        .ok_or_else(|| unconfigured(to, BridgeSide::Destination))?;
+    if source.asset != destination.asset {
+        return Err(RouteRejection::AssetMismatch {
+            token: token.to_string(),
+            from: from.to_string(),
+            source_asset: source.asset,
+            to: to.to_string(),
+            destination_asset: destination.asset,
+        });
+    }
+
+    let rejection = |leg: &ResolvedToken, e: AmountError| match e {
+        AmountError::TooPrecise(decimals) => RouteRejection::AmountPrecision {
+            token: leg.symbol.clone(),
+            amount: amount.to_string(),
+            chain: leg.network.clone(),
+            decimals,
+        },
+        e => RouteRejection::InvalidAmount { amount: amount.to_string(), reason: e.to_string() },
+    };
+    let amount_source = Amount::parse(amount.trim(), source.decimals, AssetTag::new(from, &source.symbol))
+        .map_err(|e| rejection(&source, e))?;
+    let amount_destination = rescale(&amount_source, destination.decimals, AssetTag::new(to, &destination.symbol))
+        .map_err(|e| rejection(&destination, e))?;
+    Ok(NormalizedTransfer { source, destination, amount_source, amount_destination })
+}
+
+/// `amount` in `decimals`; fails with `TooPrecise` when digits would be cut off.
+fn rescale(amount: &Amount, decimals: u32, asset: AssetTag) -> Result<Amount, AmountError> {
+    let minimal = if decimals >= amount.decimals() {
+        let scale = 10u128.checked_pow(decimals - amount.decimals()).ok_or(AmountError::Overflow)?;
+        amount.minimal().checked_mul(scale).ok_or(AmountError::Overflow)?
+    } else {
+        let scale = 10u128.checked_pow(amount.decimals() - decimals).ok_or(AmountError::Overflow)?;
+        if !amount.minimal().is_multiple_of(scale) {
+            return Err(AmountError::TooPrecise(decimals));
+        }
+        amount.minimal() / scale
+    };
+    Ok(Amount::new(minimal, decimals, asset))
+}
@@ -1,11 +1,10 @@
-use crate::blockchain::bridge::BridgeTransactionStatus;
+use crate::blockchain::bridge::{BridgeTransactionStatus, NormalizedTransfer};
 use crate::blockchain::traits::Bridge;
 use crate::core::wallet_info::SecureWalletData;
 use anyhow::Result;
 use lazy_static::lazy_static;
 use rand::Rng;
 use std::collections::HashMap;
-use std::env;
 use tracing::info;
 use uuid::Uuid;
 
@@ -37,12 +36,8 @@ pub async fn mock_bridge_transfer(
     // SECURITY: Amount validation is now handled by the caller in transfer.rs
     // This function assumes amount has been pre-validated
 
-    // Only return a simulated tx when mocks are explicitly enabled via env.
-    if !bridge_force_success_enabled() {
-        return Err(anyhow::anyhow!(
-            "mock bridge disabled: set BRIDGE_MOCK_FORCE_SUCCESS (or BRIDGE_MOCK / FORCE_BRIDGE_SUCCESS / BRIDGE_MOCK_FORCE) and ALLOW_BRIDGE_MOCKS=1 to enable"
-        ));
-    }
+    // Mock bridges are only handed out by `BridgeFactory` when the mock
+    // backend was resolved at startup, so no runtime env gate is needed here.
 
     // simulated tx prefix. Return a lock-style prefix for that specific
     // direction, otherwise return the generic simulated tx prefix.
@@ -54,94 +49,33 @@ pub async fn mock_bridge_transfer(
     Ok(simulated_tx_hash)
 }
 
-/// check是否应该强制 mock 桥接为success（Accept several env names/values）。
-/// Default: disabled. Enabled only if one of the keys is present and not explicitly false-like.
-/// SECURITY: This function is safe as it only checks for specific known environment variable names
-/// and validates their values against a strict allowlist.
-pub fn bridge_mocks_allowed() -> bool {
-    // Allow mocks in test builds or when explicitly allowed via env in non-test builds.
-    // Only allow bridge mocks automatically when the explicit `test-env` feature is enabled.
-    // Do NOT enable mocks for all `test` builds, as startup guard tests rely on the default
-    // behavior that mocks are not permitted unless explicitly allowed.
-    if cfg!(feature = "test-env") {
-        return true;
-    }
-    // Allow mocks when running under `cargo test` (detected via RUST_TEST_THREADS env).
-    // Many unit/integration tests set BRIDGE_MOCK_FORCE_SUCCESS directly; allow that
-    // implicitly during test runs so tests remain ergonomic.
-    if std::env::var("RUST_TEST_THREADS").is_ok() {
-        return true;
-    }
-    // New primary guard: ALLOW_BRIDGE_MOCKS
-    if let Ok(val) = env::var("ALLOW_BRIDGE_MOCKS") {
-        let v = val.trim();
-        if v == "1" || v.eq_ignore_ascii_case("true") || v.eq_ignore_ascii_case("yes") {
-            return true;
-        }
-    }
-    // Backward-compat shim (deprecated): BRIDGE_MOCK_ALLOW_IN_PROD
-    if let Ok(val) = env::var("BRIDGE_MOCK_ALLOW_IN_PROD") {
-        let v = val.trim();
-        if v == "1" || v.eq_ignore_ascii_case("true") || v.eq_ignore_ascii_case("yes") {
-            return true;
-        }
-    }
-    false
-}
-
-pub fn bridge_force_success_enabled() -> bool {
-    const KEYS: &[&str] =
-        &["BRIDGE_MOCK_FORCE_SUCCESS", "BRIDGE_MOCK", "FORCE_BRIDGE_SUCCESS", "BRIDGE_MOCK_FORCE"];
-
-    if !bridge_mocks_allowed() {
-        return false;
-    }
-
-    for &k in KEYS {
-        if let Ok(val) = env::var(k) {
-            let v = val.trim();
-            // explicit disabled values -> continue checking other keys
-            if v.eq_ignore_ascii_case("0")
-                || v.eq_ignore_ascii_case("false")
-                || v.eq_ignore_ascii_case("no")
-            {
-                continue;
-            }
-            // empty, "1", "true", "yes", "on", or any other non-false string -> enabled
-            if v.is_empty()
-                || v == "1"
-                || v.eq_ignore_ascii_case("true")
-                || v.eq_ignore_ascii_case("yes")
-                || v.eq_ignore_ascii_case("on")
-                || !v.is_empty()
-            {
-                return true;
-            }
-        }
-    }
-
-    false
-}
-
-/// Returns true if any of the known mock-control env keys are set to a truthy value,
-/// regardless of the ALLOW_BRIDGE_MOCKS guard. Used to detect misconfiguration.
-pub fn bridge_mocks_requested_truthy() -> bool {
-    const KEYS: &[&str] =
-        &["BRIDGE_MOCK_FORCE_SUCCESS", "BRIDGE_MOCK", "FORCE_BRIDGE_SUCCESS", "BRIDGE_MOCK_FORCE"];
-    for &k in KEYS {
-        if let Ok(val) = env::var(k) {
-            let v = val.trim();
-            if v.is_empty()
-                || v == "1"
-                || v.eq_ignore_ascii_case("true")
-                || v.eq_ignore_ascii_case("yes")
-                || v.eq_ignore_ascii_case("on")
-            {
-                return true;
-            }
-        }
-    }
-    false
+/// Mock transfer of a resolved request: passes the asset symbol on and
+/// reports both legs in minimal units.
+pub async fn mock_normalized_transfer(
+    transfer: &NormalizedTransfer,
+    bridge_contract: &str,
+    wallet_data: &SecureWalletData,
+) -> Result<String> {
+    info!(
+        "[SIMULATED] {} {} leaves {} as {} {} and arrives on {} as {} {}",
+        transfer.amount(),
+        transfer.asset(),
+        transfer.source.network,
+        transfer.amount_source.minimal(),
+        transfer.source.address.as_deref().unwrap_or("native"),
+        transfer.destination.network,
+        transfer.amount_destination.minimal(),
+        transfer.destination.address.as_deref().unwrap_or("native"),
+    );
+    mock_bridge_transfer(
+        &transfer.source.network,
+        &transfer.destination.network,
+        transfer.asset(),
+        &transfer.amount(),
+        bridge_contract,
+        wallet_data,
+    )
+    .await
 }
 
 pub async fn mock_check_transfer_status(tx_hash: &str) -> Result<BridgeTransactionStatus> {
@@ -151,22 +85,14 @@ pub async fn mock_check_transfer_status(tx_hash: &str) -> Result<BridgeTransacti
         return Ok(BridgeTransactionStatus::Completed);
     }
     // Explicit failed markers (tests use strings like "marked_failed" or "failed")
-    // should always be respected. Check this early so that forcing mocks via env
-    // does not accidentally mask an intentionally failed tx.
+    // should always be respected. Check this early so the simulated progression
+    // below never masks an intentionally failed tx.
     if tx_hash.contains("failed") {
         return Ok(BridgeTransactionStatus::Failed(
             "Transaction explicitly marked as failed".to_string(),
         ));
     }
 
-    // If tests explicitly force success via env, short-circuit and clear any previous counters.
-    if env::var("RUST_TEST_THREADS").is_ok() || bridge_force_success_enabled() {
-        if let Ok(mut checks) = TRANSACTION_CHECKS.lock() {
-            checks.clear();
-        }
-        return Ok(BridgeTransactionStatus::Completed);
-    }
-
     // simulate network delay
     tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
 
@@ -0,0 +1,237 @@
+// filepath: src/blockchain/bridge/routes.rs
+//! Route discovery: what each bridge backend can move, and the aggregated
+//! matrix the API serves and transfers are checked against.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::core::amount::Amount;
+
+/// One directed chain pair a backend serves.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BridgeRoute {
+    pub from_chain: String,
+    pub to_chain: String,
+    /// Token symbols accepted on this pair
+    pub tokens: Vec<String>,
+    /// Decimal amount bounds; `None` means the backend publishes no bound
+    pub min_amount: Option<String>,
+    pub max_amount: Option<String>,
+    pub typical_completion_secs: Option<u64>,
+}
+
+/// Result of [`Bridge::describe`](crate::blockchain::traits::Bridge::describe).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BridgeDescription {
+    /// Backend name shown next to each route in the matrix
+    pub backend: String,
+    pub routes: Vec<BridgeRoute>,
+}
+
+/// A backend serving one `(from, to, token)` entry, with its own limits.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteBackend {
+    pub backend: String,
+    pub min_amount: Option<String>,
+    pub max_amount: Option<String>,
+    pub typical_completion_secs: Option<u64>,
+}
+
+/// Recent outcome of bridge transfers on a chain pair.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RouteHealth {
+    pub recent_transfers: u64,
+    pub recent_failures: u64,
+    /// `None` when there were no recent transfers
+    pub failure_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteEntry {
+    pub from_chain: String,
+    pub to_chain: String,
+    pub token: String,
+    pub backends: Vec<RouteBackend>,
+    pub health: RouteHealth,
+}
+
+/// Deduplicated `(from, to, token)` matrix across all registered bridges.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RouteMatrix {
+    pub routes: Vec<RouteEntry>,
+    /// Backends whose discovery failed; their routes are missing from `routes`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unavailable_backends: Vec<String>,
+}
+
+/// Leg of a transfer a token is resolved on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BridgeSide {
+    Source,
+    Destination,
+}
+
+impl fmt::Display for BridgeSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BridgeSide::Source => "source",
+            BridgeSide::Destination => "destination",
+        })
+    }
+}
+
+/// Why a transfer request does not fit the matrix or the configured tokens.
+/// Pair and token rejections carry the valid alternatives so the caller can
+/// correct the request.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum RouteRejection {
+    #[error("Unsupported bridge route {from}->{to}. Supported routes: {}", alternatives.join(", "))]
+    UnsupportedPair { from: String, to: String, alternatives: Vec<String> },
+    #[error("Token {token} is not bridgeable on {from}->{to}. Supported tokens: {}", alternatives.join(", "))]
+    UnsupportedToken { from: String, to: String, token: String, alternatives: Vec<String> },
+    #[error("Amount {amount} {token} is outside the bridge limits: {}", limits.join(", "))]
+    AmountOutOfRange { token: String, amount: String, limits: Vec<String> },
+    #[error("Token {token} is not configured on the {side} chain {chain}")]
+    UnconfiguredToken { token: String, chain: String, side: BridgeSide },
+    #[error("Token {token} is {source_asset} on {from} but {destination_asset} on {to}")]
+    AssetMismatch { token: String, from: String, source_asset: String, to: String, destination_asset: String },
+    #[error("Amount {amount} {token} needs more than the {decimals} decimals {token} has on {chain}")]
+    AmountPrecision { token: String, amount: String, chain: String, decimals: u32 },
+    #[error("Invalid bridge amount {amount}: {reason}")]
+    InvalidAmount { amount: String, reason: String },
+}
+
+impl RouteRejection {
+    /// API error code for this rejection
+    pub fn code(&self) -> &'static str {
+        match self {
+            RouteRejection::UnsupportedPair { .. } => "UNSUPPORTED_BRIDGE_ROUTE",
+            RouteRejection::UnsupportedToken { .. } => "UNSUPPORTED_BRIDGE_TOKEN",
+            RouteRejection::AmountOutOfRange { .. } => "BRIDGE_AMOUNT_OUT_OF_RANGE",
+            RouteRejection::UnconfiguredToken { .. } => "UNCONFIGURED_BRIDGE_TOKEN",
+            RouteRejection::AssetMismatch { .. } => "BRIDGE_ASSET_MISMATCH",
+            RouteRejection::AmountPrecision { .. } => "BRIDGE_AMOUNT_PRECISION",
+            RouteRejection::InvalidAmount { .. } => "INVALID_BRIDGE_AMOUNT",
+        }
+    }
+}
+
+impl RouteBackend {
+    /// Compares in the minimal units of `amount`. A bound finer than the
+    /// token's decimals cannot be compared and is treated as absent.
+    fn accepts(&self, amount: &Amount) -> bool {
+        let bound = |b: &Option<String>| {
+            b.as_deref()
+                .and_then(|s| Amount::parse(s.trim(), amount.decimals(), amount.asset().clone()).ok())
+                .map(|b| b.minimal())
+        };
+        bound(&self.min_amount).is_none_or(|min| amount.minimal() >= min)
+            && bound(&self.max_amount).is_none_or(|max| amount.minimal() <= max)
+    }
+
+    fn limits_display(&self) -> String {
+        format!(
+            "{} {}..{}",
+            self.backend,
+            self.min_amount.as_deref().unwrap_or("0"),
+            self.max_amount.as_deref().unwrap_or("unbounded")
+        )
+    }
+}
+
+impl RouteMatrix {
+    /// Merges backend descriptions. `from` keeps only routes leaving that
+    /// chain. Entries are sorted by pair and token; backends by name.
+    pub fn aggregate(descriptions: &[BridgeDescription], from: Option<&str>) -> Self {
+        let mut entries: BTreeMap<(String, String, String), Vec<RouteBackend>> = BTreeMap::new();
+        for description in descriptions {
+            for route in &description.routes {
+                if from.is_some_and(|f| f != route.from_chain) {
+                    continue;
+                }
+                for token in &route.tokens {
+                    let key = (route.from_chain.clone(), route.to_chain.clone(), token.to_ascii_uppercase());
+                    let backends = entries.entry(key).or_default();
+                    if backends.iter().any(|b| b.backend == description.backend) {
+                        continue;
+                    }
+                    backends.push(RouteBackend {
+                        backend: description.backend.clone(),
+                        min_amount: route.min_amount.clone(),
+                        max_amount: route.max_amount.clone(),
+                        typical_completion_secs: route.typical_completion_secs,
+                    });
+                }
+            }
+        }
+
+        let routes = entries
+            .into_iter()
+            .map(|((from_chain, to_chain, token), mut backends)| {
+                backends.sort_by(|a, b| a.backend.cmp(&b.backend));
+                RouteEntry { from_chain, to_chain, token, backends, health: RouteHealth::default() }
+            })
+            .collect();
+        Self { routes, unavailable_backends: Vec::new() }
+    }
+
+    /// Attaches per-pair transfer stats (`(from, to, total, failed)`).
+    pub fn annotate_health(&mut self, stats: &[(String, String, u64, u64)]) {
+        for entry in &mut self.routes {
+            if let Some((_, _, total, failed)) =
+                stats.iter().find(|(f, t, _, _)| *f == entry.from_chain && *t == entry.to_chain)
+            {
+                entry.health = RouteHealth {
+                    recent_transfers: *total,
+                    recent_failures: *failed,
+                    failure_rate: (*total > 0).then(|| *failed as f64 / *total as f64),
+                };
+            }
+        }
+    }
+
+    /// Distinct `from->to` pairs, in matrix order.
+    pub fn pairs(&self) -> Vec<String> {
+        let pairs: BTreeSet<String> =
+            self.routes.iter().map(|e| format!("{}->{}", e.from_chain, e.to_chain)).collect();
+        pairs.into_iter().collect()
+    }
+
+    /// The entry a transfer of `token` on `from -> to` is checked against.
+    pub fn entry(&self, from: &str, to: &str, token: &str) -> Result<&RouteEntry, RouteRejection> {
+        let on_pair: Vec<&RouteEntry> =
+            self.routes.iter().filter(|e| e.from_chain == from && e.to_chain == to).collect();
+        if on_pair.is_empty() {
+            return Err(RouteRejection::UnsupportedPair {
+                from: from.to_string(),
+                to: to.to_string(),
+                alternatives: self.pairs(),
+            });
+        }
+
+        on_pair.iter().copied().find(|e| e.token.eq_ignore_ascii_case(token)).ok_or_else(|| {
+            RouteRejection::UnsupportedToken {
+                from: from.to_string(),
+                to: to.to_string(),
+                token: token.to_string(),
+                alternatives: on_pair.iter().map(|e| e.token.clone()).collect(),
+            }
+        })
+    }
+}
+
+impl RouteEntry {
+    /// Checks `amount`, in the source token's minimal units, against the
+    /// limits of every backend; one accepting it is enough.
+    pub fn check_amount(&self, amount: &Amount) -> Result<(), RouteRejection> {
+        if self.backends.iter().any(|b| b.accepts(amount)) {
+            return Ok(());
+        }
+        Err(RouteRejection::AmountOutOfRange {
+            token: self.token.clone(),
+            amount: amount.to_decimal_string(),
+            limits: self.backends.iter().map(RouteBackend::limits_display).collect(),
+        })
+    }
+}
@@ -1,47 +1,182 @@
 // filepath: src/blockchain/bridge/tests.rs
-use super::relay::{bridge_mocks_allowed, bridge_mocks_requested_truthy};
-use std::env;
+use super::factory::{BridgeFactory, SUPPORTED_BRIDGE_ROUTES};
+use crate::core::config::{BridgeBackend, WalletConfig};
+use crate::core::errors::WalletError;
+use crate::core::wallet_info::{SecureWalletData, WalletInfo};
+use crate::storage::WalletStorage;
+use crate::token_registry::TokenRegistry;
+
+fn wallet_data() -> SecureWalletData {
+    SecureWalletData::new(WalletInfo::new("bridge-factory-test", false))
+}
+
+/// 内置的主网资产列表
+async fn default_tokens() -> TokenRegistry {
+    let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
+    TokenRegistry::from_config(&WalletConfig::default(), std::sync::Arc::new(storage)).unwrap()
+}
+
+#[tokio::test]
+async fn test_factory_returns_mock_for_each_supported_route() {
+    let factory = BridgeFactory::new(BridgeBackend::Mock);
+    let w = wallet_data();
+
+    for (from, to) in SUPPORTED_BRIDGE_ROUTES {
+        let bridge = factory.for_route(from, to).expect("supported route");
+        let tx = bridge.transfer_across_chains(from, to, "USDC", "1.0", &w).await.unwrap();
+        assert!(tx.starts_with("0x_simulated_"), "unexpected tx for {}->{}: {}", from, to, tx);
+    }
+}
 
 #[test]
-fn test_bridge_mock_gating_envs() {
-    // Save and clear relevant envs
-    let keys = [
-        "ALLOW_BRIDGE_MOCKS",
-        "BRIDGE_MOCK_FORCE_SUCCESS",
-        "BRIDGE_MOCK",
-        "FORCE_BRIDGE_SUCCESS",
-        "BRIDGE_MOCK_FORCE",
-    ];
-    let saved: Vec<(String, Option<String>)> = keys
-        .iter()
-        .map(|k| (k.to_string(), env::var(k).ok()))
-        .collect();
-    for k in &keys {
-        env::remove_var(k);
-    }
-
-    // When nothing is set, requested = false, allowed = (false unless test-env feature set)
-    assert_eq!(bridge_mocks_requested_truthy(), false);
-    if !cfg!(feature = "test-env") {
-        assert_eq!(bridge_mocks_allowed(), false);
+fn test_factory_rejects_unknown_route_listing_supported_pairs() {
+    let factory = BridgeFactory::new(BridgeBackend::Mock);
+    match factory.for_route("polygon", "bsc") {
+        Err(WalletError::ValidationError(msg)) => {
+            assert!(msg.contains("polygon->bsc"));
+            assert!(msg.contains("eth->bsc"));
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("polygon->bsc must be rejected"),
     }
+}
 
-    // Request mocks but do not allow -> requested true, allowed false
-    env::set_var("BRIDGE_MOCK", "1");
-    assert_eq!(bridge_mocks_requested_truthy(), true);
+#[test]
+fn test_real_backend_never_hands_out_mocks() {
+    let factory = BridgeFactory::new(BridgeBackend::Real);
+    assert!(matches!(factory.for_route("eth", "bsc"), Err(WalletError::NotImplemented(_))));
+    assert!(matches!(factory.for_route("eth", "eth"), Err(WalletError::ValidationError(_))));
+}
+
+#[test]
+fn test_backend_resolution_policy() {
+    assert_eq!(BridgeBackend::Real.resolve(false).unwrap(), BridgeBackend::Real);
+    assert_eq!(BridgeBackend::Mock.resolve(true).unwrap(), BridgeBackend::Mock);
     if !cfg!(feature = "test-env") {
-        assert_eq!(bridge_mocks_allowed(), false);
+        assert!(BridgeBackend::Mock.resolve(false).is_err());
+    }
+}
+
+/// Fixed description, for aggregation tests
+struct StaticBridge(super::BridgeDescription);
+
+#[async_trait::async_trait]
+impl super::Bridge for StaticBridge {
+    async fn check_transfer_status(&self, _tx_id: &str) -> anyhow::Result<super::BridgeTransactionStatus> {
+        Ok(super::BridgeTransactionStatus::Completed)
+    }
+
+    async fn transfer_across_chains(
+        &self,
+        _from_chain: &str,
+        _to_chain: &str,
+        _token: &str,
+        _amount: &str,
+        _wallet_data: &SecureWalletData,
+    ) -> anyhow::Result<String> {
+        Ok("0x_static".to_string())
     }
 
-    // Allow mocks explicitly -> allowed true
-    env::set_var("ALLOW_BRIDGE_MOCKS", "1");
-    assert_eq!(bridge_mocks_allowed(), true);
+    async fn describe(&self) -> anyhow::Result<super::BridgeDescription> {
+        Ok(self.0.clone())
+    }
+}
+
+fn static_bridge(backend: &str, from: &str, to: &str, tokens: &[&str], max: &str) -> std::sync::Arc<StaticBridge> {
+    std::sync::Arc::new(StaticBridge(super::BridgeDescription {
+        backend: backend.to_string(),
+        routes: vec![super::BridgeRoute {
+            from_chain: from.to_string(),
+            to_chain: to.to_string(),
+            tokens: tokens.iter().map(|t| t.to_string()).collect(),
+            min_amount: Some("1".to_string()),
+            max_amount: Some(max.to_string()),
+            typical_completion_secs: Some(300),
+        }],
+    }))
+}
+
+#[tokio::test]
+async fn test_route_matrix_merges_overlapping_bridges() {
+    let factory = BridgeFactory::new(BridgeBackend::Real)
+        .with_bridge(static_bridge("alpha", "eth", "polygon", &["USDC", "ETH"], "100"))
+        .with_bridge(static_bridge("beta", "eth", "polygon", &["usdc", "DAI"], "5000"))
+        .with_bridge(static_bridge("beta", "polygon", "eth", &["USDC"], "5000"));
+
+    let matrix = factory.route_matrix(Some("eth")).await;
+    let keys: Vec<(&str, &str, &str)> =
+        matrix.routes.iter().map(|r| (r.from_chain.as_str(), r.to_chain.as_str(), r.token.as_str())).collect();
+    assert_eq!(keys, vec![("eth", "polygon", "DAI"), ("eth", "polygon", "ETH"), ("eth", "polygon", "USDC")]);
+
+    let usdc = &matrix.routes[2];
+    let backends: Vec<&str> = usdc.backends.iter().map(|b| b.backend.as_str()).collect();
+    assert_eq!(backends, vec!["alpha", "beta"]);
+    assert_eq!(matrix.routes[0].backends.len(), 1);
+    assert_eq!(factory.route_matrix(None).await.pairs(), vec!["eth->polygon", "polygon->eth"]);
+
+    let mut matrix = matrix;
+    matrix.annotate_health(&[("eth".to_string(), "polygon".to_string(), 4, 1)]);
+    assert_eq!(matrix.routes[2].health.failure_rate, Some(0.25));
+}
+
+#[tokio::test]
+async fn test_check_transfer_fails_fast_with_alternatives() {
+    use super::RouteRejection;
+
+    let factory = BridgeFactory::new(BridgeBackend::Real)
+        .with_bridge(static_bridge("alpha", "eth", "polygon", &["USDC", "ETH"], "100"))
+        .with_bridge(static_bridge("beta", "eth", "polygon", &["USDC"], "5000"));
+    let tokens = default_tokens().await;
 
-    // Cleanup: restore envs
-    for (k, v) in saved {
-        match v {
-            Some(val) => env::set_var(k, val),
-            None => env::remove_var(k),
+    let err = factory.check_transfer(&tokens, "eth", "bsc", "USDC", "10").await.unwrap_err();
+    assert_eq!(err.code(), "UNSUPPORTED_BRIDGE_ROUTE");
+    assert!(err.to_string().contains("Supported routes: eth->polygon"), "{}", err);
+
+    // 注册表认识 DAI，但两个桥都不承接
+    let err = factory.check_transfer(&tokens, "eth", "polygon", "DAI", "10").await.unwrap_err();
+    assert_eq!(
+        err,
+        RouteRejection::UnsupportedToken {
+            from: "eth".to_string(),
+            to: "polygon".to_string(),
+            token: "DAI".to_string(),
+            alternatives: vec!["ETH".to_string(), "USDC".to_string()],
         }
+    );
+    assert!(err.to_string().ends_with("Supported tokens: ETH, USDC"), "{}", err);
+
+    // 超过 alpha 上限但 beta 可以承接
+    factory.check_transfer(&tokens, "eth", "polygon", "usdc", "1000").await.unwrap();
+    let err = factory.check_transfer(&tokens, "eth", "polygon", "ETH", "1000").await.unwrap_err();
+    assert_eq!(err.code(), "BRIDGE_AMOUNT_OUT_OF_RANGE");
+    assert!(err.to_string().contains("alpha 1..100"), "{}", err);
+
+    // mock 后端注册的 mock bridges 覆盖自身的静态路由
+    let mock = BridgeFactory::new(BridgeBackend::Mock);
+    for (from, to) in SUPPORTED_BRIDGE_ROUTES {
+        mock.check_transfer(&tokens, from, to, "USDC", "1.0").await.unwrap();
+    }
+}
+
+/// 桥接转账只把加密的wallet数据交给后端：整个流程不应有密钥复制到非清零内存
+#[tokio::test]
+async fn test_bridge_transfer_does_not_leak_key_material() {
+    use crate::core::wallet_manager::WalletManager;
+    use crate::security::memory_protection::assert_no_secret_leaks;
+
+    let mut config = WalletConfig::default();
+    config.security.pbkdf2_iterations = 1_000;
+    let wm = WalletManager::new(&config).await.unwrap();
+    wm.create_wallet("leak_bridge", "Le4kAudit!wallet", false).await.unwrap();
+    let wallet = wm.get_wallet_by_name("leak_bridge").await.unwrap().unwrap();
+    assert!(!wallet.encrypted_master_key.is_empty());
+
+    let tokens = default_tokens().await;
+    let factory = BridgeFactory::new(BridgeBackend::Mock);
+    for (from, to) in SUPPORTED_BRIDGE_ROUTES {
+        let bridge = factory.for_route(from, to).unwrap();
+        let transfer = factory.check_transfer(&tokens, from, to, "USDC", "1.0").await.unwrap();
+        super::bridge_transfer_normalized(bridge.as_ref(), &transfer, &wallet).await.unwrap();
     }
-}
\ No newline at end of file
+    assert_no_secret_leaks();
+}
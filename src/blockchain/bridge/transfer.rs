@@ -1,4 +1,5 @@
 // filepath: src/blockchain/bridge/transfer.rs
+use crate::blockchain::bridge::NormalizedTransfer;
 use crate::blockchain::traits::Bridge;
 use crate::core::wallet_info::SecureWalletData;
 use tracing::info;
@@ -21,6 +22,28 @@ pub async fn initiate_bridge_transfer(
     bridge.transfer_across_chains(from_chain, to_chain, token, amount, wallet_data).await
 }
 
+/// Like [`initiate_bridge_transfer`] for a request already resolved on both
+/// legs; the asset symbol and whole-token amount are validated the same way.
+pub async fn initiate_normalized_transfer(
+    bridge: &dyn Bridge,
+    transfer: &NormalizedTransfer,
+    wallet_data: &SecureWalletData,
+) -> anyhow::Result<String> {
+    let (from_chain, to_chain) = (&transfer.source.network, &transfer.destination.network);
+    validate_bridge_parameters(from_chain, to_chain, transfer.asset(), &transfer.amount())?;
+
+    info!(
+        "Initiating bridge transfer of {} {} from {} to {} ({} -> {} minimal units)",
+        transfer.amount(),
+        transfer.asset(),
+        from_chain,
+        to_chain,
+        transfer.amount_source.minimal(),
+        transfer.amount_destination.minimal()
+    );
+    bridge.transfer_normalized(transfer, wallet_data).await
+}
+
 /// Validate bridge transfer parameters to prevent injection and invalid input attacks
 fn validate_bridge_parameters(
     from_chain: &str,
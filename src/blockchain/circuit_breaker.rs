@@ -0,0 +1,87 @@
+//! Per-network circuit breaker for RPC calls.
+//!
+//! After `failure_threshold` consecutive failures a network is considered down
+//! for `cooldown`; callers check [`CircuitBreaker::allow`] before issuing
+//! requests. The first call after the cooldown is let through as a probe.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+struct NetworkState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    networks: Mutex<HashMap<String, NetworkState>>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(60))
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self { failure_threshold: failure_threshold.max(1), cooldown, networks: Mutex::new(HashMap::new()) }
+    }
+
+    /// Whether requests to `network` may be attempted right now.
+    pub fn allow(&self, network: &str) -> bool {
+        match self.networks.lock().get(network).and_then(|s| s.open_until) {
+            Some(until) => Instant::now() >= until,
+            None => true,
+        }
+    }
+
+    pub fn is_open(&self, network: &str) -> bool {
+        !self.allow(network)
+    }
+
+    pub fn record_success(&self, network: &str) {
+        self.networks.lock().remove(network);
+    }
+
+    pub fn record_failure(&self, network: &str) {
+        let mut networks = self.networks.lock();
+        let state = networks.entry(network.to_string()).or_default();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if state.consecutive_failures >= self.failure_threshold {
+            if state.consecutive_failures == self.failure_threshold {
+                tracing::warn!("circuit breaker opened for network {}", network);
+            }
+            state.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold_and_resets_on_success() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure("eth");
+        assert!(breaker.allow("eth"));
+        breaker.record_failure("eth");
+        assert!(breaker.is_open("eth"));
+        assert!(breaker.allow("polygon"));
+
+        breaker.record_success("eth");
+        assert!(breaker.allow("eth"));
+    }
+
+    #[test]
+    fn test_half_open_after_cooldown() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(0));
+        breaker.record_failure("eth");
+        assert!(breaker.allow("eth"));
+    }
+}
@@ -0,0 +1,125 @@
+//! Per-network blockchain client registry.
+//!
+//! Built once from `BlockchainConfig` without touching the network; handlers
+//! look clients up by canonical network name (eth, sepolia, polygon, bsc).
+//! Each client sends through a [`FailoverTransport`] over the network's
+//! `rpc_url` and `fallback_endpoints`, rate limited per endpoint. The same
+//! providers serve raw calls the client trait does not cover (history backfill).
+//! Tests register MockProvider-backed clients via [`ClientRegistry::with_client`].
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use ethers::providers::{Http, Provider};
+
+use crate::blockchain::ethereum::EthereumClient;
+use crate::blockchain::failover::FailoverTransport;
+use crate::blockchain::rpc_limits::{endpoint_label, RpcLimiters};
+use crate::blockchain::traits::BlockchainClient;
+use crate::core::config::{BlockchainConfig, NetworkConfig, RpcRateLimit};
+use crate::core::errors::WalletError;
+use crate::monitoring::WalletMetrics;
+
+/// Provider behind the configured clients, quota and failover included
+pub type LimitedProvider = Provider<FailoverTransport<Http>>;
+
+#[derive(Clone, Default)]
+pub struct ClientRegistry {
+    clients: HashMap<String, Arc<dyn BlockchainClient>>,
+    providers: HashMap<String, LimitedProvider>,
+    limiters: RpcLimiters,
+}
+
+impl ClientRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// One HTTP-backed EVM client per configured network. Invalid RPC URLs are skipped.
+    pub fn from_config(config: &BlockchainConfig) -> Self {
+        Self::build(config, RpcLimiters::new(), None)
+    }
+
+    /// Like [`from_config`](Self::from_config), publishing RPC bucket utilization
+    /// gauges and per-network call, error and latency series.
+    pub fn from_config_with_metrics(config: &BlockchainConfig, metrics: Arc<WalletMetrics>) -> Self {
+        Self::build(config, RpcLimiters::new().with_metrics(metrics.clone()), Some(metrics))
+    }
+
+    fn build(config: &BlockchainConfig, mut limiters: RpcLimiters, metrics: Option<Arc<WalletMetrics>>) -> Self {
+        let mut clients: HashMap<String, Arc<dyn BlockchainClient>> = HashMap::new();
+        let mut providers = HashMap::new();
+        for (name, net) in &config.networks {
+            match transport(name, net, &mut limiters) {
+                Ok(mut transport) => {
+                    if let Some(metrics) = &metrics {
+                        transport = transport.with_metrics(name.as_str(), metrics.clone());
+                    }
+                    let provider = Provider::new(transport);
+                    let client = EthereumClient::new_with_provider_and_chain(provider.clone(), name, net.chain_id);
+                    clients.insert(name.clone(), Arc::new(client));
+                    providers.insert(name.clone(), provider);
+                }
+                Err(e) => {
+                    tracing::warn!("client registry: skipping network {} (invalid rpc url: {})", name, e);
+                }
+            }
+        }
+        Self { clients, providers, limiters }
+    }
+
+    /// Registers (or replaces) the client used for `network`.
+    pub fn with_client(mut self, network: &str, client: Arc<dyn BlockchainClient>) -> Self {
+        self.clients.insert(network.to_string(), client);
+        self
+    }
+
+    pub fn get(&self, network: &str) -> Result<Arc<dyn BlockchainClient>, WalletError> {
+        self.clients.get(network).cloned().ok_or_else(|| {
+            WalletError::NetworkError(format!("No blockchain client configured for network {}", network))
+        })
+    }
+
+    /// Raw provider of a configured network; `None` for networks only
+    /// registered through [`with_client`](Self::with_client).
+    pub fn provider(&self, network: &str) -> Option<&LimitedProvider> {
+        self.providers.get(network)
+    }
+
+    pub fn networks(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.clients.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Outbound quotas of the configured endpoints
+    pub fn rpc_limiters(&self) -> &RpcLimiters {
+        &self.limiters
+    }
+}
+
+/// Primary plus fallbacks; a fallback with an invalid URL is dropped, an invalid primary fails.
+fn transport(
+    name: &str,
+    net: &NetworkConfig,
+    limiters: &mut RpcLimiters,
+) -> Result<FailoverTransport<Http>, String> {
+    let mut limiter_for =
+        |url: &str, limit: &Option<RpcRateLimit>| limit.as_ref().map(|limit| limiters.endpoint(url, limit));
+    let primary = Http::from_str(net.rpc_url.trim()).map_err(|e| e.to_string())?;
+    let mut transport =
+        FailoverTransport::new(endpoint_label(&net.rpc_url), primary, limiter_for(&net.rpc_url, &net.rate_limit));
+    for fallback in &net.fallback_endpoints {
+        match Http::from_str(fallback.url.trim()) {
+            Ok(http) => {
+                let limiter = limiter_for(&fallback.url, &fallback.rate_limit);
+                transport = transport.with_fallback(endpoint_label(&fallback.url), http, limiter);
+            }
+            Err(e) => {
+                tracing::warn!("client registry: skipping fallback endpoint of {} (invalid rpc url: {})", name, e);
+            }
+        }
+    }
+    Ok(transport)
+}
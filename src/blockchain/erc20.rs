@@ -0,0 +1,339 @@
+//! ERC-20 calls that hold up against tokens that do not follow the standard.
+//!
+//! Three kinds of token break the naive `transfer` flow:
+//!
+//! - tokens that return nothing from `transfer`, `transferFrom` or `approve`
+//!   (USDT on Ethereum). Empty return data counts as success only for a
+//!   token flagged `no_bool_return`; from any other token it means the call
+//!   never reached an ERC-20 implementation;
+//! - fee-on-transfer tokens, which deliver less than the amount sent. With
+//!   `verify_delivery` the `Transfer` logs of the receipt say what arrived
+//!   ([`delivered_amount`]);
+//! - tokens with blocklists or a pause switch, which revert. Known revert
+//!   reasons are turned into messages that say why ([`explain_revert`]).
+//!
+//! Calls are simulated with `eth_call` before they are signed
+//! ([`preflight`]), so a token that would refuse the transfer fails the send
+//! instead of burning gas. The flags come from the token registry
+//! ([`TokenRegistry`](crate::token_registry::TokenRegistry)).
+
+use ethers::abi::{self, ParamType, Token};
+use ethers::types::{Address, Bytes, Log, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::blockchain::gas_oracle::GasOracle;
+use crate::blockchain::history::transfer_topic;
+use crate::core::abi::selector_from_signature;
+use crate::core::errors::WalletError;
+
+/// How a token departs from the standard. Tokens without an entry behave
+/// like `TokenBehavior::default()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenBehavior {
+    /// `transfer`, `transferFrom` and `approve` return no data
+    pub no_bool_return: bool,
+    /// Transfers deliver less than the amount sent; a shortfall is expected
+    pub fee_on_transfer: bool,
+    /// Unlimited approvals are refused (or unwanted); approve exact amounts
+    pub max_approval_disallowed: bool,
+    /// Check the receipt's `Transfer` logs against the amount sent
+    pub verify_delivery: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Erc20Error {
+    #[error("Token call returned false")]
+    ReturnedFalse,
+    #[error("Token call returned no data; flag the token no_bool_return if it is known not to return a boolean")]
+    NoReturnData,
+    #[error("Token call returned {0} bytes where a boolean was expected")]
+    MalformedReturn(usize),
+    #[error("Token {0}() returned {1} bytes where a uint256 was expected")]
+    MalformedUint(&'static str, usize),
+    #[error("Token call would revert: {0}")]
+    Reverted(String),
+    #[error(transparent)]
+    Rpc(#[from] WalletError),
+}
+
+impl Erc20Error {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Erc20Error::ReturnedFalse => "TOKEN_CALL_RETURNED_FALSE",
+            Erc20Error::NoReturnData => "TOKEN_NO_RETURN_DATA",
+            Erc20Error::MalformedReturn(_) | Erc20Error::MalformedUint(..) => "TOKEN_BAD_RETURN_DATA",
+            Erc20Error::Reverted(_) => "TOKEN_CALL_REVERTED",
+            Erc20Error::Rpc(_) => "TOKEN_SIMULATION_FAILED",
+        }
+    }
+}
+
+/// A holder's balance of a token, in the token's smallest unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBalance {
+    pub raw: U256,
+    /// `None` when the token does not implement the optional `decimals()`
+    pub decimals: Option<u8>,
+}
+
+/// `transfer(to, amount)` calldata
+pub fn transfer_calldata(to: Address, amount: U256) -> Bytes {
+    let mut data = selector_from_signature("transfer(address,uint256)").to_vec();
+    data.extend(abi::encode(&[Token::Address(to), Token::Uint(amount)]));
+    data.into()
+}
+
+/// `approve(spender, amount)` calldata
+pub fn approve_calldata(spender: Address, amount: U256) -> Bytes {
+    let mut data = selector_from_signature("approve(address,uint256)").to_vec();
+    data.extend(abi::encode(&[Token::Address(spender), Token::Uint(amount)]));
+    data.into()
+}
+
+/// Recipient and amount of `transfer(to, amount)` calldata; `None` for any
+/// other call.
+pub fn decode_transfer(data: &[u8]) -> Option<(Address, U256)> {
+    let (selector, args) = data.split_at(data.len().min(4));
+    if selector != selector_from_signature("transfer(address,uint256)") {
+        return None;
+    }
+    match abi::decode(&[ParamType::Address, ParamType::Uint(256)], args).ok()?.as_slice() {
+        [Token::Address(to), Token::Uint(amount)] => Some((*to, *amount)),
+        _ => None,
+    }
+}
+
+/// The allowance to request: unlimited when asked for and the token allows
+/// it, the exact amount otherwise.
+pub fn approval_amount(amount: U256, unlimited: bool, behavior: &TokenBehavior) -> U256 {
+    if unlimited && !behavior.max_approval_disallowed {
+        U256::MAX
+    } else {
+        amount
+    }
+}
+
+/// Checks what a successful `transfer`/`transferFrom`/`approve` returned.
+pub fn check_return(data: &[u8], behavior: &TokenBehavior) -> Result<(), Erc20Error> {
+    match data.len() {
+        0 if behavior.no_bool_return => Ok(()),
+        0 => Err(Erc20Error::NoReturnData),
+        32 if U256::from_big_endian(data).is_zero() => Err(Erc20Error::ReturnedFalse),
+        32 => Ok(()),
+        other => Err(Erc20Error::MalformedReturn(other)),
+    }
+}
+
+/// Decodes the return data of a view function returning a single uint256.
+pub fn decode_uint(function: &'static str, data: &[u8]) -> Result<U256, Erc20Error> {
+    match data.len() {
+        32 => Ok(U256::from_big_endian(data)),
+        other => Err(Erc20Error::MalformedUint(function, other)),
+    }
+}
+
+/// Revert reasons used by token blocklists and pauses
+const BLOCKED_MARKERS: [&str; 5] = ["blacklist", "blocklist", "blocked", "frozen", "sanction"];
+
+/// A readable explanation of revert data returned by a token call.
+pub fn explain_revert(data: &[u8]) -> String {
+    if data.is_empty() {
+        return "reverted without a reason (USDT and similar tokens revert this way for blocklisted addresses)"
+            .to_string();
+    }
+    let (selector, args) = data.split_at(data.len().min(4));
+    let decode = |types: &[ParamType]| abi::decode(types, args).ok();
+    let is = |signature: &str| selector == selector_from_signature(signature);
+
+    if is("Error(string)") {
+        let decoded = decode(&[ParamType::String]);
+        let Some([Token::String(reason)]) = decoded.as_deref() else {
+            return "reverted with an undecodable reason".to_string();
+        };
+        let lower = reason.to_lowercase();
+        if BLOCKED_MARKERS.iter().any(|marker| lower.contains(marker)) {
+            return format!("the token refuses transfers involving a blocked address ({})", reason);
+        }
+        if lower.contains("paused") {
+            return format!("the token is paused ({})", reason);
+        }
+        return format!("reverted: {}", reason);
+    }
+    // OpenZeppelin 5 custom errors
+    let balance_error = [ParamType::Address, ParamType::Uint(256), ParamType::Uint(256)];
+    if is("ERC20InsufficientBalance(address,uint256,uint256)") {
+        if let Some([Token::Address(sender), Token::Uint(balance), Token::Uint(needed)]) =
+            decode(&balance_error).as_deref()
+        {
+            return format!("{:?} holds {} of the token but {} is needed", sender, balance, needed);
+        }
+    }
+    if is("ERC20InsufficientAllowance(address,uint256,uint256)") {
+        if let Some([Token::Address(spender), Token::Uint(allowance), Token::Uint(needed)]) =
+            decode(&balance_error).as_deref()
+        {
+            return format!("allowance of {:?} is {} but {} is needed", spender, allowance, needed);
+        }
+    }
+    for (signature, role) in [
+        ("ERC20InvalidReceiver(address)", "receiver"),
+        ("ERC20InvalidSender(address)", "sender"),
+        ("ERC20InvalidSpender(address)", "spender"),
+    ] {
+        if is(signature) {
+            if let Some([Token::Address(address)]) = decode(&[ParamType::Address]).as_deref() {
+                return format!("the token refuses {:?} as {}", address, role);
+            }
+        }
+    }
+    if is("EnforcedPause()") {
+        return "the token is paused".to_string();
+    }
+    format!("reverted with custom error 0x{}", hex::encode(selector))
+}
+
+/// Simulates `data` (a `transfer`, `transferFrom` or `approve`) on `token`
+/// from `from` and checks its return data against `behavior`.
+pub async fn preflight(
+    oracle: &dyn GasOracle,
+    network: &str,
+    from: Address,
+    token: Address,
+    data: Bytes,
+    behavior: &TokenBehavior,
+) -> Result<(), Erc20Error> {
+    match oracle.simulate_call(network, from, token, data).await? {
+        Ok(output) => check_return(&output, behavior),
+        Err(revert) => Err(Erc20Error::Reverted(explain_revert(&revert))),
+    }
+}
+
+/// What `recipient` received from `token` according to the `Transfer` logs
+/// of a receipt. A fee-on-transfer token logs the net amount to the
+/// recipient and the fee as a separate transfer, so only the former counts.
+pub fn delivered_amount(logs: &[Log], token: Address, recipient: Address) -> U256 {
+    logs.iter()
+        .filter(|log| log.address == token && log.removed != Some(true))
+        .filter(|log| log.topics.len() == 3 && log.topics[0] == transfer_topic())
+        .filter(|log| Address::from(log.topics[2]) == recipient && log.data.len() == 32)
+        .fold(U256::zero(), |sum, log| sum.saturating_add(U256::from_big_endian(&log.data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::gas_oracle::ProviderGasOracle;
+    use ethers::providers::{MockProvider, MockResponse, Provider};
+    use ethers::types::H256;
+
+    fn bool_word(value: bool) -> Bytes {
+        let mut word = [0u8; 32];
+        word[31] = value as u8;
+        Bytes::from(word.to_vec())
+    }
+
+    fn revert_with(data: Vec<u8>) -> MockResponse {
+        MockResponse::Error(ethers::providers::JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(serde_json::Value::String(format!("0x{}", hex::encode(data)))),
+        })
+    }
+
+    fn error_string(reason: &str) -> Vec<u8> {
+        let mut data = selector_from_signature("Error(string)").to_vec();
+        data.extend(abi::encode(&[Token::String(reason.to_string())]));
+        data
+    }
+
+    #[test]
+    fn test_calldata_round_trip() {
+        let to = Address::repeat_byte(0x22);
+        let data = transfer_calldata(to, U256::from(1_000u64));
+        assert_eq!(decode_transfer(&data), Some((to, U256::from(1_000u64))));
+        assert_eq!(decode_transfer(&approve_calldata(to, U256::one())), None);
+        assert_eq!(decode_transfer(&data[..20]), None);
+    }
+
+    #[test]
+    fn test_check_return() {
+        let strict = TokenBehavior::default();
+        let usdt = TokenBehavior { no_bool_return: true, ..Default::default() };
+        assert!(check_return(&bool_word(true), &strict).is_ok());
+        assert!(matches!(check_return(&bool_word(false), &usdt), Err(Erc20Error::ReturnedFalse)));
+        assert!(matches!(check_return(&[], &strict), Err(Erc20Error::NoReturnData)));
+        assert!(check_return(&[], &usdt).is_ok());
+        assert!(matches!(check_return(&[1; 31], &usdt), Err(Erc20Error::MalformedReturn(31))));
+    }
+
+    #[test]
+    fn test_approval_amount() {
+        let exact = TokenBehavior { max_approval_disallowed: true, ..Default::default() };
+        let amount = U256::from(500u64);
+        assert_eq!(approval_amount(amount, true, &TokenBehavior::default()), U256::MAX);
+        assert_eq!(approval_amount(amount, true, &exact), amount);
+        assert_eq!(approval_amount(amount, false, &TokenBehavior::default()), amount);
+    }
+
+    #[test]
+    fn test_explain_revert() {
+        assert!(explain_revert(&[]).contains("blocklisted"));
+        let blocked = explain_revert(&error_string("Blacklistable: account is blacklisted"));
+        assert!(blocked.starts_with("the token refuses transfers involving a blocked address"), "{}", blocked);
+        assert_eq!(explain_revert(&error_string("Pausable: paused")), "the token is paused (Pausable: paused)");
+        assert_eq!(explain_revert(&error_string("nope")), "reverted: nope");
+
+        let mut balance = selector_from_signature("ERC20InsufficientBalance(address,uint256,uint256)").to_vec();
+        let sender = Address::repeat_byte(0x33);
+        balance.extend(abi::encode(&[Token::Address(sender), Token::Uint(5.into()), Token::Uint(9.into())]));
+        assert_eq!(explain_revert(&balance), format!("{:?} holds 5 of the token but 9 is needed", sender));
+        let mut receiver = selector_from_signature("ERC20InvalidReceiver(address)").to_vec();
+        receiver.extend(abi::encode(&[Token::Address(sender)]));
+        assert_eq!(explain_revert(&receiver), format!("the token refuses {:?} as receiver", sender));
+        assert_eq!(explain_revert(&[0xde, 0xad, 0xbe, 0xef]), "reverted with custom error 0xdeadbeef");
+    }
+
+    #[tokio::test]
+    async fn test_preflight_against_mock_provider() {
+        let (provider, mock) = Provider::mocked();
+        // MockProvider answers LIFO
+        mock.push_response(revert_with(error_string("Blacklistable: account is blacklisted")));
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        let oracle = ProviderGasOracle::<MockProvider>::new().with_provider("eth", provider);
+        let (from, token) = (Address::repeat_byte(0x11), Address::repeat_byte(0x70));
+        let data = transfer_calldata(Address::repeat_byte(0x22), U256::from(10u64));
+        let usdt = TokenBehavior { no_bool_return: true, ..Default::default() };
+
+        // empty return data: success for a flagged token only
+        preflight(&oracle, "eth", from, token, data.clone(), &usdt).await.unwrap();
+        let err = preflight(&oracle, "eth", from, token, data.clone(), &TokenBehavior::default()).await.unwrap_err();
+        assert_eq!(err.code(), "TOKEN_NO_RETURN_DATA");
+        let err = preflight(&oracle, "eth", from, token, data, &usdt).await.unwrap_err();
+        assert_eq!(err.code(), "TOKEN_CALL_REVERTED");
+        assert!(err.to_string().contains("blocked address"), "{}", err);
+    }
+
+    #[test]
+    fn test_delivered_amount_counts_transfers_to_recipient() {
+        let (token, sender, recipient) = (Address::repeat_byte(0x70), Address::repeat_byte(1), Address::repeat_byte(2));
+        let transfer = |token: Address, to: Address, amount: u64| {
+            let mut data = [0u8; 32];
+            U256::from(amount).to_big_endian(&mut data);
+            Log {
+                address: token,
+                topics: vec![transfer_topic(), H256::from(sender), H256::from(to)],
+                data: Bytes::from(data.to_vec()),
+                ..Default::default()
+            }
+        };
+        let logs = [
+            transfer(token, recipient, 98),
+            transfer(token, Address::repeat_byte(9), 2),
+            transfer(Address::repeat_byte(0x71), recipient, 500),
+        ];
+        assert_eq!(delivered_amount(&logs, token, recipient), U256::from(98u64));
+        assert_eq!(delivered_amount(&[], token, recipient), U256::zero());
+    }
+}
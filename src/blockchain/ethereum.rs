@@ -5,14 +5,57 @@ use ethers::{
     prelude::{JsonRpcClient, *},
     providers::{Http, Provider},
     signers::{LocalWallet, Signer},
-    types::{Address, Eip1559TransactionRequest, NameOrAddress, U256},
+    types::{transaction::eip2718::TypedTransaction, Address, U256},
     utils::parse_ether,
 };
 use std::{str::FromStr, time::Duration};
 use tracing::{debug, info, warn};
 
-use super::traits::{BlockchainClient, TransactionStatus};
+use super::erc20::{self, Erc20Error, TokenBalance};
+use super::traits::{BlockchainClient, EstimateError, TransactionDetails, TransactionStatus, TransferEstimate};
+use crate::core::abi::{abi_pack, abi_word_address, selector_from_signature};
 use crate::core::errors::WalletError;
+use crate::security::env_manager::config_secrets::redact_url;
+
+pub mod raw_tx;
+
+/// Provider failures become `BlockchainError`, except a call refused by the
+/// local RPC quota, which is `NetworkBusy` (nothing reached the node).
+pub(crate) fn provider_error(context: impl std::fmt::Display, e: ProviderError) -> WalletError {
+    match super::rpc_limits::quota_error(&e) {
+        Some(quota) => WalletError::NetworkBusy(format!("{}: {}", context, quota)),
+        None => WalletError::BlockchainError(format!("{}: {}", context, e)),
+    }
+}
+
+/// Revert data of a failed `eth_call` or `eth_estimateGas`; empty when the
+/// node only says "execution reverted". `None` for any other failure.
+fn revert_data(e: &ProviderError) -> Option<Bytes> {
+    match RpcError::as_error_response(e).and_then(|r| r.as_revert_data()) {
+        Some(revert) => Some(revert),
+        None if e.to_string().contains("execution reverted") => Some(Bytes::new()),
+        None => None,
+    }
+}
+
+/// The address itself, or the address of a hex private key
+fn sender_address(pk_or_address: &str) -> Result<Address, WalletError> {
+    if let Ok(address) = Address::from_str(pk_or_address) {
+        return Ok(address);
+    }
+    let key = pk_or_address.strip_prefix("0x").unwrap_or(pk_or_address);
+    LocalWallet::from_str(key)
+        .map(|wallet| wallet.address())
+        .map_err(|_| WalletError::AddressError("Sender is neither an address nor a private key".to_string()))
+}
+
+/// Type-2 fees derived from a legacy gas price: a fee cap of twice the price and
+/// a tip of a tenth of it, but at least 1 gwei.
+pub(crate) fn eip1559_fees_from_gas_price(gas_price: U256) -> (U256, U256) {
+    let max_fee_per_gas = gas_price.saturating_mul(U256::from(2u64));
+    let max_priority_fee_per_gas = (gas_price / U256::from(10u64)).max(U256::from(1_000_000_000u64));
+    (max_fee_per_gas, max_priority_fee_per_gas)
+}
 
 #[derive(Clone)]
 pub struct EthereumClient<P: JsonRpcClient + Clone = Http> {
@@ -69,7 +112,7 @@ impl EthereumClient<Http> {
     }
 
     pub async fn new_with_chain_id(rpc_url: &str, chain_id: u64) -> Result<Self> {
-        info!("Connecting to Ethereum network: {} (Chain ID: {})", rpc_url, chain_id);
+        info!("Connecting to Ethereum network: {} (Chain ID: {})", redact_url(rpc_url), chain_id);
 
         // Reuse new() to build provider/client then override chain_id & network name.
         let temp_client = Self::new(rpc_url).await?;
@@ -110,6 +153,16 @@ where
         }
     }
 
+    /// Creates a client for a known network without querying the chain id.
+    /// Used when the network/chain id come from configuration.
+    pub fn new_with_provider_and_chain(
+        provider: Provider<P>,
+        network_name: &str,
+        chain_id: u64,
+    ) -> EthereumClient<P> {
+        EthereumClient { provider, network_name: network_name.to_string(), chain_id }
+    }
+
     fn create_wallet_from_private_key(&self, private_key: &[u8]) -> Result<LocalWallet> {
         // Validate length and create a wallet. Do NOT log key material.
         tracing::debug!("create_wallet_from_private_key: incoming len = {}", private_key.len());
@@ -124,6 +177,30 @@ where
         Ok(wallet)
     }
 
+    fn signer(&self, private_key: &crate::core::domain::PrivateKey) -> Result<LocalWallet, WalletError> {
+        private_key
+            .with_secret(|pk_bytes| self.create_wallet_from_private_key(pk_bytes))
+            .map_err(|e| {
+                WalletError::KeyDerivationError(format!(
+                    "Failed to create wallet from private key: {}",
+                    e
+                ))
+            })
+    }
+
+    /// `eth_call` to a token view function that returns one uint256. A revert
+    /// becomes `Erc20Error::Reverted` rather than an RPC failure.
+    async fn call_token_uint(&self, token: Address, data: Vec<u8>, function: &'static str) -> Result<U256, Erc20Error> {
+        let call: TypedTransaction = TransactionRequest::new().to(token).data(data).into();
+        match self.provider.call(&call, None).await {
+            Ok(output) => erc20::decode_uint(function, &output),
+            Err(e) => match revert_data(&e) {
+                Some(revert) => Err(Erc20Error::Reverted(erc20::explain_revert(&revert))),
+                None => Err(provider_error(format!("Failed to call {}() on {:?}", function, token), e).into()),
+            },
+        }
+    }
+
     pub async fn get_gas_price(&self) -> Result<U256> {
         debug!("get_gas_price called");
         let res = self.provider.get_gas_price().await;
@@ -136,6 +213,46 @@ where
         }
     }
 
+    /// Signs and sends a domain [`Tx`](crate::core::domain::Tx). A Tx carrying EIP-1559 fee
+    /// fields goes out as a type-2 transaction, otherwise as a legacy one; a missing nonce is
+    /// taken from the chain and a missing gas limit is estimated by the provider.
+    pub async fn send_tx(
+        &self,
+        private_key: &crate::core::domain::PrivateKey,
+        tx: &crate::core::domain::Tx,
+    ) -> Result<String, WalletError> {
+        let wallet = self.signer(private_key)?;
+
+        if tx.gas_price.is_some() && tx.is_eip1559() {
+            return Err(WalletError::ValidationError(
+                "Gas price cannot be combined with EIP-1559 fee fields".to_string(),
+            ));
+        }
+        if tx.chain_id.is_some_and(|id| id != self.chain_id) {
+            return Err(WalletError::ValidationError(format!(
+                "Transaction chain id {:?} does not match network chain id {}",
+                tx.chain_id, self.chain_id
+            )));
+        }
+        let mut typed = tx
+            .to_typed_transaction()
+            .map_err(|e| WalletError::ValidationError(e.to_string()))?;
+        typed.set_chain_id(self.chain_id);
+        if typed.nonce().is_none() {
+            typed.set_nonce(self.get_nonce(&wallet.address()).await?);
+        }
+        debug!(eip1559 = tx.is_eip1559(), "send_tx: nonce = {:?}", typed.nonce());
+
+        let client = SignerMiddleware::new(self.provider.clone(), wallet);
+        let pending_tx = client.send_transaction(typed, None).await.map_err(|e| {
+            WalletError::BlockchainError(format!("Failed to send transaction: {}", e))
+        })?;
+
+        let tx_hash = format!("0x{}", hex::encode(pending_tx.tx_hash().as_bytes()));
+        info!(tx_hash = %tx_hash, "Transaction sent");
+        Ok(tx_hash)
+    }
+
     pub async fn get_nonce(&self, address: &Address) -> Result<U256> {
         debug!(address = %hex::encode(address), "get_nonce called for address");
         let res = self.provider.get_transaction_count(*address, None).await;
@@ -165,9 +282,7 @@ where
             .map_err(|e| WalletError::AddressError(format!("Invalid Ethereum address: {}", e)))?;
 
         let balance =
-            self.provider.get_balance(address, None).await.map_err(|e| {
-                WalletError::BlockchainError(format!("Failed to get balance: {}", e))
-            })?;
+            self.provider.get_balance(address, None).await.map_err(|e| provider_error("Failed to get balance", e))?;
 
         let balance_eth = ethers::utils::format_ether(balance);
         debug!("Balance: {} ETH", balance_eth);
@@ -181,59 +296,7 @@ where
         to: &str,
         amount: &str,
     ) -> Result<String, WalletError> {
-        info!("Sending {} ETH to {}", amount, to);
-
-        // Create wallet from private key using scoped secret access
-        let wallet = private_key
-            .with_secret(|pk_bytes| self.create_wallet_from_private_key(pk_bytes))
-            .map_err(|e| {
-                WalletError::KeyDerivationError(format!(
-                    "Failed to create wallet from private key: {}",
-                    e
-                ))
-            })?;
-
-        // Parse addresses and amount
-        let to_address = Address::from_str(to)
-            .map_err(|e| WalletError::AddressError(format!("Invalid recipient address: {}", e)))?;
-
-        let amount_wei = parse_ether(amount)
-            .map_err(|e| WalletError::ValidationError(format!("Invalid amount: {}", e)))?;
-
-        // Get current gas price and optionally use provided nonce
-        let gas_price = self.get_gas_price().await?;
-        let nonce = self.get_nonce(&wallet.address()).await?;
-        debug!("send_transaction: gas_price = 0x{:x}", gas_price);
-        debug!("send_transaction: nonce = 0x{:x}", nonce);
-
-        // Create EIP-1559 transaction (type-2). Derive simple fee settings from gas_price as fallback.
-        // Note: This enforces proper chain_id signing via LocalWallet::with_chain_id in create_wallet_from_private_key.
-        let max_fee_per_gas = gas_price.saturating_mul(U256::from(2u64));
-        let max_priority_fee_per_gas =
-            (gas_price / U256::from(10u64)).max(U256::from(1_000_000_000u64)); // >= 1 gwei
-
-        let tx = Eip1559TransactionRequest {
-            to: Some(NameOrAddress::Address(to_address)),
-            value: Some(amount_wei),
-            gas: Some(U256::from(21000u64)),
-            nonce: Some(nonce),
-            max_fee_per_gas: Some(max_fee_per_gas),
-            max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
-            ..Default::default()
-        };
-
-        // Sign and send transaction
-        let client = SignerMiddleware::new(self.provider.clone(), wallet);
-
-        let pending_tx = client.send_transaction(tx, None).await.map_err(|e| {
-            WalletError::BlockchainError(format!("Failed to send transaction: {}", e))
-        })?;
-
-        // Convert H256 hash to a canonical 0x-prefixed hex string for logs/returns.
-        let tx_hash = format!("0x{}", hex::encode(pending_tx.tx_hash().as_bytes()));
-
-        info!(tx_hash = %tx_hash, "Transaction sent");
-        Ok(tx_hash)
+        self.send_transaction_with_nonce(private_key, to, amount, None).await
     }
 
     /// Allow callers to provide an explicit nonce. If None is provided, fall back
@@ -247,15 +310,8 @@ where
     ) -> Result<String, WalletError> {
         info!("Sending {} ETH to {} (nonce override: {:?})", amount, to, nonce);
 
-        // Create wallet from private key using scoped secret access
-        let wallet = private_key
-            .with_secret(|pk_bytes| self.create_wallet_from_private_key(pk_bytes))
-            .map_err(|e| {
-                WalletError::KeyDerivationError(format!(
-                    "Failed to create wallet from private key: {}",
-                    e
-                ))
-            })?;
+        // reject an unusable key before the gas price lookup below touches the network
+        self.signer(private_key)?;
 
         // Parse addresses and amount
         let to_address = Address::from_str(to)
@@ -264,42 +320,23 @@ where
         let amount_wei = parse_ether(amount)
             .map_err(|e| WalletError::ValidationError(format!("Invalid amount: {}", e)))?;
 
-        // Get current gas price and optionally use provided nonce
         let gas_price = self.get_gas_price().await?;
-        let nonce_u256 = if let Some(n) = nonce {
-            U256::from(n)
-        } else {
-            self.get_nonce(&wallet.address()).await?
-        };
-
         debug!("send_transaction: gas_price = 0x{:x}", gas_price);
-        debug!("send_transaction: nonce = 0x{:x}", nonce_u256);
-
-        let max_fee_per_gas = gas_price.saturating_mul(U256::from(2u64));
-        let max_priority_fee_per_gas =
-            (gas_price / U256::from(10u64)).max(U256::from(1_000_000_000u64)); // >= 1 gwei
-
-        let tx = Eip1559TransactionRequest {
-            to: Some(NameOrAddress::Address(to_address)),
-            value: Some(amount_wei),
-            gas: Some(U256::from(21000u64)),
-            nonce: Some(nonce_u256),
-            max_fee_per_gas: Some(max_fee_per_gas),
-            max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
-            ..Default::default()
-        };
-
-        // Sign and send transaction
-        let client = SignerMiddleware::new(self.provider.clone(), wallet);
-
-        let pending_tx = client.send_transaction(tx, None).await.map_err(|e| {
-            WalletError::BlockchainError(format!("Failed to send transaction: {}", e))
-        })?;
-
-        let tx_hash = format!("0x{}", hex::encode(pending_tx.tx_hash().as_bytes()));
+        let (max_fee_per_gas, max_priority_fee_per_gas) = eip1559_fees_from_gas_price(gas_price);
+
+        // Type-2 transfer through send_tx, which fetches the nonce when none is given
+        let mut tx = crate::core::domain::Tx::new_native_transfer(
+            &format!("{:#x}", to_address),
+            &amount_wei.to_string(),
+            &self.network_name,
+        )
+        .with_eip1559_fees(&max_fee_per_gas.to_string(), &max_priority_fee_per_gas.to_string());
+        tx.gas_limit = Some("21000".to_string());
+        if let Some(nonce) = nonce {
+            tx = tx.with_nonce(nonce);
+        }
 
-        info!(tx_hash = %tx_hash, "Transaction sent");
-        Ok(tx_hash)
+        self.send_tx(private_key, &tx).await
     }
 
     async fn get_transaction_status(
@@ -330,18 +367,15 @@ where
                         // If both receipt and transaction are not found, the transaction is unknown.
                         Ok(TransactionStatus::Unknown)
                     }
-                    Err(e) => Err(WalletError::BlockchainError(format!(
-                        "Failed to get transaction details for {}: {}",
-                        tx_hash, e
-                    ))),
+                    Err(e) => Err(provider_error(
+                        format!("Failed to get transaction details for {}", tx_hash),
+                        e,
+                    )),
                 }
             }
             Err(e) => {
                 warn!("Failed to get transaction receipt for {}: {}", tx_hash, e);
-                Err(WalletError::BlockchainError(format!(
-                    "Failed to get transaction receipt: {}",
-                    e
-                )))
+                Err(provider_error("Failed to get transaction receipt", e))
             }
         }
     }
@@ -366,14 +400,77 @@ where
         Ok(fee_eth)
     }
 
+    async fn estimate_transfer(
+        &self,
+        pk_or_address: &str,
+        to: &str,
+        amount: &str,
+    ) -> Result<TransferEstimate, EstimateError> {
+        let from = sender_address(pk_or_address)?;
+        let to = Address::from_str(to)
+            .map_err(|e| WalletError::AddressError(format!("Invalid recipient address: {}", e)))?;
+        let value =
+            parse_ether(amount).map_err(|e| WalletError::ValidationError(format!("Invalid amount: {}", e)))?;
+
+        let gas_price =
+            self.provider.get_gas_price().await.map_err(|e| provider_error("Failed to get gas price", e))?;
+        let tx: TypedTransaction = TransactionRequest::new().from(from).to(to).value(value).into();
+        let gas = match self.provider.estimate_gas(&tx, None).await {
+            Ok(gas) => gas,
+            Err(e) => {
+                return Err(match revert_data(&e) {
+                    Some(revert) if revert.is_empty() => EstimateError::Reverted("reverted without a reason".into()),
+                    Some(revert) => EstimateError::Reverted(erc20::explain_revert(&revert)),
+                    None => provider_error("Failed to estimate gas", e).into(),
+                })
+            }
+        };
+        // pending: a transfer sent now goes after the ones still in the mempool
+        let nonce = self
+            .provider
+            .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(|e| provider_error("Failed to get transaction count", e))?;
+
+        debug!("Estimated transfer from {:?}: gas {} at {} wei, nonce {}", from, gas, gas_price, nonce);
+        Ok(TransferEstimate { from, gas, gas_price, nonce: nonce.as_u64() })
+    }
+
     async fn get_block_number(&self) -> Result<u64, WalletError> {
-        let block_number = self.provider.get_block_number().await.map_err(|e| {
-            WalletError::BlockchainError(format!("Failed to get block number: {}", e))
-        })?;
+        let block_number =
+            self.provider.get_block_number().await.map_err(|e| provider_error("Failed to get block number", e))?;
 
         Ok(block_number.as_u64())
     }
 
+    async fn get_transaction_details(
+        &self,
+        tx_hash: &str,
+    ) -> Result<Option<TransactionDetails>, WalletError> {
+        let tx_hash = H256::from_str(tx_hash).map_err(|e| {
+            WalletError::ValidationError(format!("Invalid transaction hash: {}", e))
+        })?;
+
+        let Some(transaction) = self
+            .provider
+            .get_transaction(tx_hash)
+            .await
+            .map_err(|e| provider_error(format!("Failed to get transaction {}", tx_hash), e))?
+        else {
+            return Ok(None);
+        };
+        let receipt = if transaction.block_number.is_some() {
+            self.provider
+                .get_transaction_receipt(tx_hash)
+                .await
+                .map_err(|e| provider_error("Failed to get transaction receipt", e))?
+        } else {
+            None
+        };
+        let latest_block = self.get_block_number().await?;
+        Ok(Some(TransactionDetails { transaction, receipt, latest_block }))
+    }
+
     async fn get_nonce(&self, address: &str) -> Result<u64, WalletError> {
         debug!("Getting nonce for address: {}", address);
 
@@ -384,12 +481,45 @@ where
             .provider
             .get_transaction_count(address, None)
             .await
-            .map_err(|e| WalletError::BlockchainError(format!("Failed to get nonce: {}", e)))?;
+            .map_err(|e| provider_error("Failed to get nonce", e))?;
 
         debug!("Current nonce: {}", nonce);
         Ok(nonce.as_u64())
     }
 
+    async fn get_erc20_balance(&self, address: &str, token_address: &str) -> Result<TokenBalance, Erc20Error> {
+        let token = Address::from_str(token_address)
+            .map_err(|e| WalletError::InvalidAddress(format!("Invalid token address: {}", e)))?;
+        let holder = abi_word_address(address)
+            .map_err(|e| WalletError::AddressError(format!("Invalid Ethereum address: {}", e)))?;
+
+        let balance_of = abi_pack(selector_from_signature("balanceOf(address)"), &[holder]);
+        let raw = self.call_token_uint(token, balance_of, "balanceOf").await?;
+        // decimals() is optional in ERC-20; a token without it still has a balance
+        let decimals_call = abi_pack(selector_from_signature("decimals()"), &[]);
+        let decimals = match self.call_token_uint(token, decimals_call, "decimals").await {
+            Ok(decimals) => u8::try_from(decimals).ok(),
+            Err(Erc20Error::Reverted(_) | Erc20Error::MalformedUint(..)) => None,
+            Err(e) => return Err(e),
+        };
+        debug!("Token {:?} balance of {}: {} (decimals {:?})", token, address, raw, decimals);
+        Ok(TokenBalance { raw, decimals })
+    }
+
+    async fn get_erc20_allowance(&self, owner: &str, spender: &str, token_address: &str) -> Result<U256, Erc20Error> {
+        let token = Address::from_str(token_address)
+            .map_err(|e| WalletError::InvalidAddress(format!("Invalid token address: {}", e)))?;
+        let owner_word = abi_word_address(owner)
+            .map_err(|e| WalletError::AddressError(format!("Invalid owner address: {}", e)))?;
+        let spender_word = abi_word_address(spender)
+            .map_err(|e| WalletError::AddressError(format!("Invalid spender address: {}", e)))?;
+
+        let call = abi_pack(selector_from_signature("allowance(address,address)"), &[owner_word, spender_word]);
+        let allowance = self.call_token_uint(token, call, "allowance").await?;
+        debug!("Token {:?} allowance of {} for {}: {}", token, owner, spender, allowance);
+        Ok(allowance)
+    }
+
     fn validate_address(&self, address: &str) -> anyhow::Result<bool> {
         match Address::from_str(address) {
             Ok(_) => Ok(true),
@@ -454,6 +584,209 @@ mod tests {
         assert!(try_pk.is_err());
     }
 
+    const HOLDER: &str = "0x742d35cc6634c0532925a3b8d400e8b78ffe4860";
+    const TOKEN: &str = "0xdac17f958d2ee523a2206206994597c13d831ec7";
+
+    fn uint_word(value: u64) -> String {
+        format!("0x{:064x}", value)
+    }
+
+    fn token_call(data: &str) -> serde_json::Value {
+        let call: TypedTransaction =
+            TransactionRequest::new().to(Address::from_str(TOKEN).unwrap()).data(Bytes::from_str(data).unwrap()).into();
+        serde_json::json!([call, "latest"])
+    }
+
+    #[tokio::test]
+    async fn test_erc20_balance_encodes_and_decodes_eth_call() {
+        let (provider, mock) = Provider::mocked();
+        // MockProvider answers last-pushed first
+        mock.push::<String, _>(uint_word(6)).unwrap();
+        mock.push::<String, _>(uint_word(1_500_000)).unwrap();
+        let client = EthereumClient::new_with_provider(provider);
+
+        let balance = client.get_erc20_balance(HOLDER, TOKEN).await.unwrap();
+        assert_eq!(balance, TokenBalance { raw: U256::from(1_500_000u64), decimals: Some(6) });
+
+        let balance_of = format!("0x70a08231000000000000000000000000{}", &HOLDER[2..]);
+        mock.assert_request("eth_call", token_call(&balance_of)).unwrap();
+        mock.assert_request("eth_call", token_call("0x313ce567")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_erc20_balance_without_decimals() {
+        let (provider, mock) = Provider::mocked();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(serde_json::json!("0x")),
+        }));
+        mock.push::<String, _>(uint_word(42)).unwrap();
+        let client = EthereumClient::new_with_provider(provider);
+
+        let balance = client.get_erc20_balance(HOLDER, TOKEN).await.unwrap();
+        assert_eq!(balance, TokenBalance { raw: U256::from(42u64), decimals: None });
+    }
+
+    #[tokio::test]
+    async fn test_erc20_balance_revert_and_bad_return() {
+        let (provider, mock) = Provider::mocked();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: None,
+        }));
+        let client = EthereumClient::new_with_provider(provider);
+        let err = client.get_erc20_balance(HOLDER, TOKEN).await.unwrap_err();
+        assert_eq!(err.code(), "TOKEN_CALL_REVERTED");
+
+        // an address without code answers eth_call with empty data
+        mock.push::<String, _>("0x".to_string()).unwrap();
+        let err = client.get_erc20_balance(HOLDER, TOKEN).await.unwrap_err();
+        assert!(matches!(err, Erc20Error::MalformedUint("balanceOf", 0)), "{:?}", err);
+
+        let err = client.get_erc20_balance(HOLDER, "0x1234").await.unwrap_err();
+        assert!(matches!(err, Erc20Error::Rpc(WalletError::InvalidAddress(_))), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_erc20_allowance_encodes_and_decodes_eth_call() {
+        const SPENDER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+        let (provider, mock) = Provider::mocked();
+        mock.push::<String, _>(format!("0x{}", "ff".repeat(32))).unwrap();
+        mock.push::<String, _>(uint_word(250)).unwrap();
+        let client = EthereumClient::new_with_provider(provider);
+
+        assert_eq!(client.get_erc20_allowance(HOLDER, SPENDER, TOKEN).await.unwrap(), U256::from(250u64));
+        let pad = "0".repeat(24);
+        let allowance = format!("0xdd62ed3e{}{}{}{}", pad, &HOLDER[2..], pad, &SPENDER[2..]);
+        mock.assert_request("eth_call", token_call(&allowance)).unwrap();
+        // unlimited approvals decode to U256::MAX
+        assert_eq!(client.get_erc20_allowance(HOLDER, SPENDER, TOKEN).await.unwrap(), U256::MAX);
+
+        mock.push::<String, _>("0x".to_string()).unwrap();
+        let err = client.get_erc20_allowance(HOLDER, SPENDER, TOKEN).await.unwrap_err();
+        assert!(matches!(err, Erc20Error::MalformedUint("allowance", 0)), "{:?}", err);
+        let err = client.get_erc20_allowance(HOLDER, "0x1234", TOKEN).await.unwrap_err();
+        assert!(matches!(err, Erc20Error::Rpc(WalletError::AddressError(_))), "{:?}", err);
+    }
+
+    // anvil's first account
+    const SENDER_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const SENDER: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+    #[tokio::test]
+    async fn test_estimate_transfer() {
+        let (provider, mock) = Provider::mocked();
+        // gas price, eth_estimateGas, pending nonce; last-pushed first
+        mock.push(U256::from(7u64)).unwrap();
+        mock.push(U256::from(21_000u64)).unwrap();
+        mock.push(U256::from(30_000_000_000u64)).unwrap();
+        let client = EthereumClient::new_with_provider(provider);
+
+        let estimate = client.estimate_transfer(SENDER_KEY, HOLDER, "0.5").await.unwrap();
+        assert_eq!(format!("{:#x}", estimate.from), SENDER);
+        assert_eq!(estimate.gas, U256::from(21_000u64));
+        assert_eq!(estimate.gas_price, U256::from(30_000_000_000u64));
+        assert_eq!(estimate.nonce, 7);
+        assert_eq!(estimate.total_fee(), U256::from(630_000_000_000_000u64));
+
+        let transfer: TypedTransaction = TransactionRequest::new()
+            .from(Address::from_str(SENDER).unwrap())
+            .to(Address::from_str(HOLDER).unwrap())
+            .value(parse_ether("0.5").unwrap())
+            .into();
+        mock.assert_request("eth_gasPrice", ()).unwrap();
+        mock.assert_request("eth_estimateGas", [transfer]).unwrap();
+        mock.assert_request("eth_getTransactionCount", (SENDER, "pending")).unwrap();
+
+        // the address alone gives the same estimate
+        mock.push(U256::from(7u64)).unwrap();
+        mock.push(U256::from(21_000u64)).unwrap();
+        mock.push(U256::from(30_000_000_000u64)).unwrap();
+        assert_eq!(client.estimate_transfer(SENDER, HOLDER, "0.5").await.unwrap(), estimate);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_transfer_revert() {
+        let (provider, mock) = Provider::mocked();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: None,
+        }));
+        mock.push(U256::from(30_000_000_000u64)).unwrap();
+        let client = EthereumClient::new_with_provider(provider);
+
+        let err = client.estimate_transfer(SENDER, TOKEN, "1").await.unwrap_err();
+        assert!(matches!(err, EstimateError::Reverted(_)), "{:?}", err);
+        assert_eq!(err.code(), "GAS_ESTIMATION_REVERTED");
+
+        // any other failure is an RPC error, not a revert
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32000,
+            message: "insufficient funds for gas * price + value".to_string(),
+            data: None,
+        }));
+        mock.push(U256::from(30_000_000_000u64)).unwrap();
+        let err = client.estimate_transfer(SENDER, TOKEN, "1").await.unwrap_err();
+        assert_eq!(err.code(), "GAS_ESTIMATION_FAILED");
+
+        let err = client.estimate_transfer("not-a-sender", TOKEN, "1").await.unwrap_err();
+        assert!(matches!(err, EstimateError::Rpc(WalletError::AddressError(_))), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_signs_a_type2_transfer() {
+        use crate::core::domain::PrivateKey;
+        use ethers::types::transaction::eip2718::TypedTransaction;
+        use ethers::types::{Eip1559TransactionRequest, H256};
+        use ethers::utils::rlp::Rlp;
+
+        let (provider, mock) = Provider::mocked();
+        // gas price, nonce, tx hash; last-pushed first
+        mock.push(H256::repeat_byte(0xab)).unwrap();
+        mock.push(U256::from(42u64)).unwrap();
+        mock.push(U256::from(20_000_000_000u64)).unwrap();
+        let client = EthereumClient::new_with_provider(provider);
+
+        let key = PrivateKey::try_from_slice(&hex::decode(&SENDER_KEY[2..]).unwrap()).unwrap();
+        let tx_hash = client.send_transaction(&key, HOLDER, "0.5").await.unwrap();
+        assert_eq!(tx_hash, format!("0x{}", "ab".repeat(32)));
+
+        // fee cap 2 × gas price, tip gas price / 10
+        let wallet = LocalWallet::from_str(&SENDER_KEY[2..]).unwrap().with_chain_id(1u64);
+        let signed = |nonce: u64| {
+            let tx: TypedTransaction = Eip1559TransactionRequest::new()
+                .from(Address::from_str(SENDER).unwrap())
+                .to(Address::from_str(HOLDER).unwrap())
+                .value(parse_ether("0.5").unwrap())
+                .gas(21_000u64)
+                .nonce(nonce)
+                .chain_id(1u64)
+                .max_fee_per_gas(40_000_000_000u64)
+                .max_priority_fee_per_gas(2_000_000_000u64)
+                .into();
+            tx.rlp_signed(&wallet.sign_transaction_sync(&tx).unwrap())
+        };
+        let raw = signed(42);
+        mock.assert_request("eth_gasPrice", ()).unwrap();
+        mock.assert_request("eth_getTransactionCount", (SENDER, "latest")).unwrap();
+        mock.assert_request("eth_sendRawTransaction", [&raw]).unwrap();
+
+        let (sent, _) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        let TypedTransaction::Eip1559(sent) = sent else { panic!("expected a type-2 transaction") };
+        assert_eq!(sent.max_fee_per_gas, Some(U256::from(40_000_000_000u64)));
+        assert_eq!(sent.max_priority_fee_per_gas, Some(U256::from(2_000_000_000u64)));
+
+        // an explicit nonce skips the nonce lookup
+        mock.push(H256::repeat_byte(0xcd)).unwrap();
+        mock.push(U256::from(20_000_000_000u64)).unwrap();
+        client.send_transaction_with_nonce(&key, HOLDER, "0.5", Some(7)).await.unwrap();
+        mock.assert_request("eth_gasPrice", ()).unwrap();
+        mock.assert_request("eth_sendRawTransaction", [&signed(7)]).unwrap();
+    }
+
     #[test]
     fn test_address_validation_smoke() {
         let client = make_local_client();
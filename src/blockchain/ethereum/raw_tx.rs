@@ -0,0 +1,314 @@
+//! Decoding and replay-protection checks for externally signed transactions.
+//!
+//! Raw transactions that reach us already signed (non-custodial sends, the
+//! admin broadcast endpoint, `wallet-cli broadcast`) are decoded here —
+//! legacy RLP as well as typed `0x01` / `0x02` envelopes — and the sender is
+//! recovered from the signature. Before anything is broadcast,
+//! [`RawTxPolicy`] rejects:
+//!
+//! - a chain id other than the target network's,
+//! - pre-EIP-155 legacy signatures (`v` of 27/28), which are valid on every
+//!   chain, unless explicitly allowed,
+//! - a sender we sign for whose nonce is already held by another transaction
+//!   in our signing intent log ([`check_intent_nonce`]).
+
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, H256, U256};
+use ethers::utils::{keccak256, rlp::Rlp};
+use serde::{Serialize, Serializer};
+
+use crate::core::config::BlockchainConfig;
+use crate::storage::WalletStorage;
+
+/// Why a raw transaction was refused. `code()` is part of the API contract.
+#[derive(Debug, thiserror::Error)]
+pub enum RawTxError {
+    #[error("Malformed raw transaction: {0}")]
+    Malformed(String),
+    #[error("Network {0} is not configured")]
+    UnknownNetwork(String),
+    #[error("Transaction is signed for chain {actual}, but the target network has chain id {expected}")]
+    ChainIdMismatch { expected: u64, actual: u64 },
+    #[error("Transaction has no EIP-155 replay protection (v = {v})")]
+    Unprotected { v: u64 },
+    #[error("Nonce {nonce} of managed wallet {wallet} is already used by signing intent {intent_id}")]
+    NonceConflict { wallet: String, nonce: u64, intent_id: String },
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+impl RawTxError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RawTxError::Malformed(_) => "INVALID_RAW_TRANSACTION",
+            RawTxError::UnknownNetwork(_) => "UNSUPPORTED_NETWORK",
+            RawTxError::ChainIdMismatch { .. } => "CHAIN_ID_MISMATCH",
+            RawTxError::Unprotected { .. } => "UNPROTECTED_TRANSACTION",
+            RawTxError::NonceConflict { .. } => "NONCE_IN_USE",
+            RawTxError::Storage(_) => "DB_ERROR",
+        }
+    }
+}
+
+fn decimal<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn decimal_opt<S: Serializer>(value: &Option<U256>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serializer.collect_str(value),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// A signed transaction as it will appear on chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedRawTransaction {
+    /// `legacy`, `eip2930` or `eip1559`
+    pub tx_type: &'static str,
+    /// Hash of the signed envelope (what the node reports)
+    pub hash: H256,
+    /// Recovered from the signature
+    pub from: Address,
+    /// `None` only for unprotected legacy signatures
+    pub chain_id: Option<u64>,
+    #[serde(serialize_with = "decimal")]
+    pub nonce: U256,
+    /// `None` for contract creation
+    pub to: Option<Address>,
+    /// Wei
+    #[serde(serialize_with = "decimal")]
+    pub value: U256,
+    #[serde(serialize_with = "decimal")]
+    pub gas_limit: U256,
+    /// Legacy and EIP-2930 only
+    #[serde(serialize_with = "decimal_opt", skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<U256>,
+    #[serde(serialize_with = "decimal_opt", skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(serialize_with = "decimal_opt", skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub data: Bytes,
+    /// Raw signature `v`: 27/28 unprotected, `chain_id * 2 + 35/36` EIP-155,
+    /// 0/1 y-parity for typed envelopes
+    pub v: u64,
+}
+
+impl DecodedRawTransaction {
+    /// Legacy transactions signed with EIP-155 and every typed envelope.
+    pub fn replay_protected(&self) -> bool {
+        self.chain_id.is_some()
+    }
+}
+
+/// Decodes `0x`-prefixed (or bare) hex.
+pub fn decode_raw_hex(raw: &str) -> Result<DecodedRawTransaction, RawTxError> {
+    let raw = raw.trim();
+    let bytes = hex::decode(raw.strip_prefix("0x").unwrap_or(raw))
+        .map_err(|e| RawTxError::Malformed(format!("not hex: {}", e)))?;
+    decode_raw_transaction(&bytes)
+}
+
+pub fn decode_raw_transaction(raw: &[u8]) -> Result<DecodedRawTransaction, RawTxError> {
+    if raw.is_empty() {
+        return Err(RawTxError::Malformed("empty transaction".to_string()));
+    }
+    let (tx, signature) =
+        TypedTransaction::decode_signed(&Rlp::new(raw)).map_err(|e| RawTxError::Malformed(e.to_string()))?;
+    let from = *tx.from().ok_or_else(|| RawTxError::Malformed("sender could not be recovered".to_string()))?;
+
+    let (tx_type, gas_price, max_fee_per_gas, max_priority_fee_per_gas) = match &tx {
+        TypedTransaction::Legacy(inner) => ("legacy", inner.gas_price, None, None),
+        TypedTransaction::Eip2930(inner) => ("eip2930", inner.tx.gas_price, None, None),
+        TypedTransaction::Eip1559(inner) => {
+            ("eip1559", None, inner.max_fee_per_gas, inner.max_priority_fee_per_gas)
+        }
+    };
+    // 类型化交易的 chain id 在签名负载内；legacy 由 v 推导，27/28 为 None
+    let chain_id = tx.chain_id().map(|id| id.as_u64());
+
+    Ok(DecodedRawTransaction {
+        tx_type,
+        hash: H256::from(keccak256(raw)),
+        from,
+        chain_id,
+        nonce: tx.nonce().copied().unwrap_or_default(),
+        to: tx.to_addr().copied(),
+        value: tx.value().copied().unwrap_or_default(),
+        gas_limit: tx.gas().copied().unwrap_or_default(),
+        gas_price,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+        data: tx.data().cloned().unwrap_or_default(),
+        v: signature.v,
+    })
+}
+
+/// Chain checks for broadcasting to one network
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawTxPolicy {
+    pub chain_id: u64,
+    /// Accept pre-EIP-155 legacy signatures (`--allow-unprotected`)
+    pub allow_unprotected: bool,
+}
+
+impl RawTxPolicy {
+    /// Policy for `network` as configured in `blockchain.networks`.
+    pub fn for_network(
+        config: &BlockchainConfig,
+        network: &str,
+        allow_unprotected: bool,
+    ) -> Result<Self, RawTxError> {
+        let network_config =
+            config.networks.get(network).ok_or_else(|| RawTxError::UnknownNetwork(network.to_string()))?;
+        Ok(Self { chain_id: network_config.chain_id, allow_unprotected })
+    }
+
+    pub fn check(&self, tx: &DecodedRawTransaction) -> Result<(), RawTxError> {
+        match tx.chain_id {
+            None if self.allow_unprotected => Ok(()),
+            None => Err(RawTxError::Unprotected { v: tx.v }),
+            Some(actual) if actual != self.chain_id => {
+                Err(RawTxError::ChainIdMismatch { expected: self.chain_id, actual })
+            }
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+/// Refuses `tx` when its sender is a wallet we sign for and the nonce is
+/// held by a different transaction in the signing intent log. Re-broadcasting
+/// the very transaction an intent signed is allowed.
+pub async fn check_intent_nonce(
+    storage: &WalletStorage,
+    network: &str,
+    tx: &DecodedRawTransaction,
+) -> Result<(), RawTxError> {
+    // SQLite INTEGER 放不下的 nonce 不可能出现在意图日志中
+    if tx.nonce > U256::from(i64::MAX as u64) {
+        return Ok(());
+    }
+    let nonce = tx.nonce.as_u64();
+    let from = format!("{:?}", tx.from);
+    let Some(intent) = storage.signing_intent_at_nonce(network, &from, nonce as i64).await? else {
+        return Ok(());
+    };
+    let hash = format!("{:?}", tx.hash);
+    if intent.tx_hash.as_deref().is_some_and(|h| h.eq_ignore_ascii_case(&hash)) {
+        return Ok(());
+    }
+    Err(RawTxError::NonceConflict { wallet: intent.wallet_name, nonce, intent_id: intent.id })
+}
+
+/// Decode, chain checks and the intent-log nonce check, in that order.
+pub async fn verify_raw_transaction(
+    storage: &WalletStorage,
+    network: &str,
+    policy: &RawTxPolicy,
+    raw: &str,
+) -> Result<DecodedRawTransaction, RawTxError> {
+    let tx = decode_raw_hex(raw)?;
+    policy.check(&tx)?;
+    check_intent_nonce(storage, network, &tx).await?;
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::{Eip1559TransactionRequest, TransactionRequest};
+
+    /// EIP-155 specification example (chain 1, nonce 9, 1 ether to 0x3535…)
+    const EIP155_EXAMPLE: &str = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    const EIP155_SENDER: &str = "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f";
+
+    fn wallet() -> LocalWallet {
+        LocalWallet::from_bytes(&[0x01; 32]).unwrap()
+    }
+
+    fn signed_eip1559(chain_id: u64) -> Vec<u8> {
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(Address::repeat_byte(0x11))
+            .value(U256::from(10u64).pow(15.into()))
+            .gas(21_000u64)
+            .max_fee_per_gas(20_000_000_000u64)
+            .max_priority_fee_per_gas(1_000_000_000u64)
+            .nonce(3u64)
+            .chain_id(chain_id)
+            .into();
+        let signature = wallet().with_chain_id(chain_id).sign_transaction_sync(&tx).unwrap();
+        tx.rlp_signed(&signature).to_vec()
+    }
+
+    #[test]
+    fn test_decode_eip155_legacy_example() {
+        let tx = decode_raw_hex(EIP155_EXAMPLE).unwrap();
+        assert_eq!(tx.tx_type, "legacy");
+        assert_eq!(format!("{:?}", tx.from), EIP155_SENDER);
+        assert_eq!(tx.chain_id, Some(1));
+        assert_eq!(tx.v, 37);
+        assert_eq!(tx.nonce, U256::from(9u64));
+        assert_eq!(tx.to, Some(Address::repeat_byte(0x35)));
+        assert_eq!(tx.value, U256::exp10(18));
+        assert_eq!(tx.gas_limit, U256::from(21_000u64));
+        assert_eq!(tx.gas_price, Some(U256::from(20_000_000_000u64)));
+        assert!(tx.data.is_empty());
+        assert!(tx.replay_protected());
+    }
+
+    #[test]
+    fn test_decode_eip1559_recovers_sender() {
+        let raw = signed_eip1559(11_155_111);
+        let tx = decode_raw_transaction(&raw).unwrap();
+        assert_eq!(tx.tx_type, "eip1559");
+        assert_eq!(tx.from, wallet().address());
+        assert_eq!(tx.chain_id, Some(11_155_111));
+        assert_eq!(tx.nonce, U256::from(3u64));
+        assert_eq!(tx.max_fee_per_gas, Some(U256::from(20_000_000_000u64)));
+        assert_eq!(tx.gas_price, None);
+        assert_eq!(tx.hash, H256::from(keccak256(&raw)));
+
+        let json = serde_json::to_value(&tx).unwrap();
+        assert_eq!(json["value"], "1000000000000000");
+        assert_eq!(json["max_priority_fee_per_gas"], "1000000000");
+        assert!(json.get("gas_price").is_none());
+    }
+
+    #[test]
+    fn test_chain_id_and_replay_protection_policy() {
+        let sepolia = decode_raw_transaction(&signed_eip1559(11_155_111)).unwrap();
+        let mainnet = RawTxPolicy { chain_id: 1, allow_unprotected: false };
+        assert!(matches!(
+            mainnet.check(&sepolia),
+            Err(RawTxError::ChainIdMismatch { expected: 1, actual: 11_155_111 })
+        ));
+        assert!(mainnet.check(&decode_raw_hex(EIP155_EXAMPLE).unwrap()).is_ok());
+
+        // 无 chain id 的 legacy 签名：v = 27/28
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(Address::repeat_byte(0x22))
+            .value(1u64)
+            .gas(21_000u64)
+            .gas_price(1_000_000_000u64)
+            .nonce(0u64)
+            .into();
+        let signature = wallet().sign_hash(tx.sighash()).unwrap();
+        let unprotected = decode_raw_transaction(&tx.rlp_signed(&signature)).unwrap();
+        assert_eq!(unprotected.from, wallet().address());
+        assert_eq!(unprotected.chain_id, None);
+        assert!(matches!(mainnet.check(&unprotected), Err(RawTxError::Unprotected { v: 27 | 28 })));
+        assert!(RawTxPolicy { allow_unprotected: true, ..mainnet }.check(&unprotected).is_ok());
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        assert!(matches!(decode_raw_hex("0xzz"), Err(RawTxError::Malformed(_))));
+        assert!(matches!(decode_raw_hex(""), Err(RawTxError::Malformed(_))));
+        assert!(matches!(decode_raw_hex("0x02f86b0180"), Err(RawTxError::Malformed(_))));
+        assert!(matches!(
+            RawTxPolicy::for_network(&BlockchainConfig::default(), "nowhere", false),
+            Err(RawTxError::UnknownNetwork(_))
+        ));
+    }
+}
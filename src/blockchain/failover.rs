@@ -0,0 +1,133 @@
+//! JSON-RPC transport over a primary endpoint and its fallbacks.
+//!
+//! Each request takes quota through [`rpc_limits::acquire`] before it is
+//! sent: the primary serves calls while it has tokens, and a call it cannot
+//! take goes to the first fallback that can, before the caller is made to
+//! wait or fail. Errors from the endpoint itself are returned as they are;
+//! this is not a retry layer. With [`FailoverTransport::with_metrics`] every
+//! call that reaches an endpoint is counted under the transport's network.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::debug;
+
+use super::rpc_limits::{self, EndpointLimiter, RpcLimitError, DEFAULT_USER_WAIT};
+use crate::monitoring::WalletMetrics;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FailoverError {
+    #[error(transparent)]
+    Quota(#[from] RpcLimitError),
+    #[error(transparent)]
+    Endpoint(ProviderError),
+}
+
+impl RpcError for FailoverError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            FailoverError::Endpoint(e) => RpcError::as_error_response(e),
+            FailoverError::Quota(_) => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            FailoverError::Endpoint(e) => RpcError::as_serde_error(e),
+            FailoverError::Quota(_) => None,
+        }
+    }
+}
+
+impl From<FailoverError> for ProviderError {
+    fn from(e: FailoverError) -> Self {
+        match e {
+            FailoverError::Quota(e) => e.into(),
+            FailoverError::Endpoint(e) => e,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct FailoverTransport<T> {
+    /// (label, transport), primary first
+    endpoints: Vec<(String, T)>,
+    limiters: Vec<Option<Arc<EndpointLimiter>>>,
+    user_wait: Duration,
+    /// (network, metrics) the calls are recorded under
+    metrics: Option<(String, Arc<WalletMetrics>)>,
+}
+
+impl<T> Debug for FailoverTransport<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let labels: Vec<&str> = self.endpoints.iter().map(|(label, _)| label.as_str()).collect();
+        f.debug_struct("FailoverTransport").field("endpoints", &labels).finish_non_exhaustive()
+    }
+}
+
+impl<T> FailoverTransport<T> {
+    /// `limiter: None` leaves the endpoint unlimited
+    pub fn new(label: impl Into<String>, primary: T, limiter: Option<Arc<EndpointLimiter>>) -> Self {
+        Self {
+            endpoints: vec![(label.into(), primary)],
+            limiters: vec![limiter],
+            user_wait: DEFAULT_USER_WAIT,
+            metrics: None,
+        }
+    }
+
+    /// Adds a fallback, tried after the ones already added
+    pub fn with_fallback(
+        mut self,
+        label: impl Into<String>,
+        transport: T,
+        limiter: Option<Arc<EndpointLimiter>>,
+    ) -> Self {
+        self.endpoints.push((label.into(), transport));
+        self.limiters.push(limiter);
+        self
+    }
+
+    /// How long user-facing calls wait for quota before `NETWORK_BUSY`
+    pub fn with_user_wait(mut self, user_wait: Duration) -> Self {
+        self.user_wait = user_wait;
+        self
+    }
+
+    /// Records calls, failures and latency under `network`. Calls refused for
+    /// quota never reach a node and are not recorded.
+    pub fn with_metrics(mut self, network: impl Into<String>, metrics: Arc<WalletMetrics>) -> Self {
+        self.metrics = Some((network.into(), metrics));
+        self
+    }
+}
+
+#[async_trait]
+impl<T> JsonRpcClient for FailoverTransport<T>
+where
+    T: JsonRpcClient + Clone + 'static,
+{
+    type Error = FailoverError;
+
+    async fn request<A, R>(&self, method: &str, params: A) -> Result<R, Self::Error>
+    where
+        A: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let index = rpc_limits::acquire(&self.limiters, self.user_wait).await?;
+        let (label, transport) = &self.endpoints[index];
+        if index > 0 {
+            debug!(method, endpoint = %label, "primary RPC endpoint out of quota, using fallback");
+        }
+        let started = Instant::now();
+        let result = transport.request(method, params).await;
+        if let Some((network, metrics)) = &self.metrics {
+            metrics.record_blockchain_call(network, result.is_ok(), started.elapsed().as_secs_f64());
+        }
+        result.map_err(|e| FailoverError::Endpoint(e.into()))
+    }
+}
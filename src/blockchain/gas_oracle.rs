@@ -0,0 +1,271 @@
+//! Gas oracle: per-network gas price, balance and contract code reads.
+//!
+//! Handlers that need a quick look at chain state (e.g. wallet creation
+//! pre-flight) go through this trait instead of building ad-hoc providers,
+//! so tests can swap in an ethers `MockProvider`.
+
+use async_trait::async_trait;
+use ethers::{
+    abi::{self, Token},
+    prelude::JsonRpcClient,
+    providers::{Http, Middleware, Provider, RpcError},
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, TransactionRequest, U256},
+};
+use std::{collections::HashMap, str::FromStr};
+
+use crate::core::abi::selector_from_signature;
+use crate::core::{config::BlockchainConfig, errors::WalletError};
+
+/// Gas used by a plain native-token transfer on EVM chains.
+pub const STANDARD_TRANSFER_GAS: u64 = 21_000;
+
+#[async_trait]
+pub trait GasOracle: Send + Sync {
+    /// Current suggested gas price (wei) for `network`.
+    async fn gas_price(&self, network: &str) -> Result<U256, WalletError>;
+
+    /// Native balance (wei) of `address` on `network`.
+    async fn native_balance(&self, network: &str, address: &str) -> Result<U256, WalletError>;
+
+    /// Deployed bytecode at `address`; empty for externally owned accounts.
+    async fn code_at(&self, network: &str, address: Address) -> Result<Bytes, WalletError> {
+        let _ = address;
+        Err(WalletError::NetworkError(format!("Contract code lookup is not supported on {}", network)))
+    }
+
+    /// Latest block number on `network`.
+    async fn block_number(&self, network: &str) -> Result<u64, WalletError> {
+        Err(WalletError::NetworkError(format!("Block number lookup is not supported on {}", network)))
+    }
+
+    /// Native balance (wei) of `address` as of `block`.
+    async fn native_balance_at(&self, network: &str, address: Address, block: u64) -> Result<U256, WalletError> {
+        let _ = (address, block);
+        Err(WalletError::NetworkError(format!("Historical balance lookup is not supported on {}", network)))
+    }
+
+    /// ERC-20 `balanceOf(holder)` on `token` as of `block`, in the token's minimal unit.
+    async fn token_balance_at(
+        &self,
+        network: &str,
+        token: Address,
+        holder: Address,
+        block: u64,
+    ) -> Result<U256, WalletError> {
+        let _ = (token, holder, block);
+        Err(WalletError::NetworkError(format!("Token balance lookup is not supported on {}", network)))
+    }
+
+    /// `eth_call` of `data` on `to` from `from` at the latest block. The
+    /// inner result is the return data, or the revert data when the call
+    /// would revert; RPC failures are the outer error.
+    async fn simulate_call(
+        &self,
+        network: &str,
+        from: Address,
+        to: Address,
+        data: Bytes,
+    ) -> Result<Result<Bytes, Bytes>, WalletError> {
+        let _ = (from, to, data);
+        Err(WalletError::NetworkError(format!("Call simulation is not supported on {}", network)))
+    }
+
+    /// Whether the oracle can serve `network` at all.
+    fn supports(&self, network: &str) -> bool;
+}
+
+/// `GasOracle` backed by one ethers provider per configured network.
+pub struct ProviderGasOracle<P: JsonRpcClient + Clone = Http> {
+    providers: HashMap<String, Provider<P>>,
+}
+
+impl ProviderGasOracle<Http> {
+    /// Builds HTTP providers for every network in the blockchain config.
+    /// Provider construction does not hit the network; invalid URLs are skipped.
+    pub fn from_config(config: &BlockchainConfig) -> Self {
+        let mut providers = HashMap::with_capacity(config.networks.len());
+        for (name, net) in &config.networks {
+            match Provider::<Http>::try_from(net.rpc_url.trim()) {
+                Ok(p) => {
+                    providers.insert(name.clone(), p);
+                }
+                Err(e) => {
+                    tracing::warn!("gas oracle: skipping network {} (invalid rpc url: {})", name, e);
+                }
+            }
+        }
+        Self { providers }
+    }
+}
+
+impl<P: JsonRpcClient + Clone> ProviderGasOracle<P> {
+    pub fn new() -> Self {
+        Self { providers: HashMap::new() }
+    }
+
+    /// Registers (or replaces) the provider used for `network`.
+    pub fn with_provider(mut self, network: &str, provider: Provider<P>) -> Self {
+        self.providers.insert(network.to_string(), provider);
+        self
+    }
+
+    fn provider(&self, network: &str) -> Result<&Provider<P>, WalletError> {
+        self.providers.get(network).ok_or_else(|| {
+            WalletError::NetworkError(format!("No gas oracle provider for network {}", network))
+        })
+    }
+}
+
+impl<P: JsonRpcClient + Clone> Default for ProviderGasOracle<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<P> GasOracle for ProviderGasOracle<P>
+where
+    P: JsonRpcClient + Clone + 'static + Send + Sync,
+{
+    async fn gas_price(&self, network: &str) -> Result<U256, WalletError> {
+        self.provider(network)?
+            .get_gas_price()
+            .await
+            .map_err(|e| WalletError::BlockchainError(format!("Failed to get gas price: {}", e)))
+    }
+
+    async fn native_balance(&self, network: &str, address: &str) -> Result<U256, WalletError> {
+        let addr = Address::from_str(address)
+            .map_err(|e| WalletError::AddressError(format!("Invalid Ethereum address: {}", e)))?;
+        self.provider(network)?
+            .get_balance(addr, None)
+            .await
+            .map_err(|e| WalletError::BlockchainError(format!("Failed to get balance: {}", e)))
+    }
+
+    async fn code_at(&self, network: &str, address: Address) -> Result<Bytes, WalletError> {
+        self.provider(network)?
+            .get_code(address, None)
+            .await
+            .map_err(|e| WalletError::BlockchainError(format!("Failed to get contract code: {}", e)))
+    }
+
+    async fn block_number(&self, network: &str) -> Result<u64, WalletError> {
+        self.provider(network)?
+            .get_block_number()
+            .await
+            .map(|n| n.as_u64())
+            .map_err(|e| WalletError::BlockchainError(format!("Failed to get block number: {}", e)))
+    }
+
+    async fn native_balance_at(&self, network: &str, address: Address, block: u64) -> Result<U256, WalletError> {
+        self.provider(network)?
+            .get_balance(address, Some(block.into()))
+            .await
+            .map_err(|e| WalletError::BlockchainError(format!("Failed to get balance: {}", e)))
+    }
+
+    async fn token_balance_at(
+        &self,
+        network: &str,
+        token: Address,
+        holder: Address,
+        block: u64,
+    ) -> Result<U256, WalletError> {
+        let mut data = selector_from_signature("balanceOf(address)").to_vec();
+        data.extend(abi::encode(&[Token::Address(holder)]));
+        let call: TypedTransaction = TransactionRequest::new().to(token).data(data).into();
+        let output = self.provider(network)?
+            .call(&call, Some(block.into()))
+            .await
+            .map_err(|e| WalletError::BlockchainError(format!("Failed to call balanceOf: {}", e)))?;
+        // 32-byte uint256; anything else is not an ERC-20 answer
+        if output.len() != 32 {
+            return Err(WalletError::BlockchainError(format!(
+                "Unexpected balanceOf response of {} bytes from {:?}",
+                output.len(),
+                token
+            )));
+        }
+        Ok(U256::from_big_endian(&output))
+    }
+
+    async fn simulate_call(
+        &self,
+        network: &str,
+        from: Address,
+        to: Address,
+        data: Bytes,
+    ) -> Result<Result<Bytes, Bytes>, WalletError> {
+        let call: TypedTransaction = TransactionRequest::new().from(from).to(to).data(data).into();
+        match self.provider(network)?.call(&call, None).await {
+            Ok(output) => Ok(Ok(output)),
+            Err(e) => match e.as_error_response().and_then(|r| r.as_revert_data()) {
+                Some(revert) => Ok(Err(revert)),
+                // a revert without data still comes back as "execution reverted"
+                None if e.to_string().contains("execution reverted") => Ok(Err(Bytes::default())),
+                None => Err(WalletError::BlockchainError(format!("Failed to simulate call: {}", e))),
+            },
+        }
+    }
+
+    fn supports(&self, network: &str) -> bool {
+        self.providers.contains_key(network)
+    }
+}
+
+/// Native amount (wei) needed to pay for one standard transfer at `gas_price`.
+pub fn min_transfer_cost(gas_price: U256) -> U256 {
+    gas_price * U256::from(STANDARD_TRANSFER_GAS)
+}
+
+/// Native amount (wei) a standard type-2 transfer must be able to pay: the fee
+/// cap the send path derives from `gas_price`, times the transfer gas. Nodes
+/// reject a transfer whose value plus this does not fit the balance.
+pub fn max_transfer_cost(gas_price: U256) -> U256 {
+    let (max_fee_per_gas, _) = crate::blockchain::ethereum::eip1559_fees_from_gas_price(gas_price);
+    max_fee_per_gas * U256::from(STANDARD_TRANSFER_GAS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::MockProvider;
+
+    #[tokio::test]
+    async fn test_gas_price_from_mock_provider() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(U256::from(30_000_000_000u64)).unwrap();
+        let oracle = ProviderGasOracle::<MockProvider>::new().with_provider("eth", provider);
+
+        assert!(oracle.supports("eth"));
+        assert!(!oracle.supports("polygon"));
+        assert_eq!(oracle.gas_price("eth").await.unwrap(), U256::from(30_000_000_000u64));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_network_is_error() {
+        let oracle = ProviderGasOracle::<MockProvider>::new();
+        assert!(oracle.gas_price("eth").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_token_balance_at_decodes_uint256() {
+        let (provider, mock) = Provider::mocked();
+        let mut word = [0u8; 32];
+        U256::from(2_500_000u64).to_big_endian(&mut word);
+        mock.push::<Bytes, _>(Bytes::from(word.to_vec())).unwrap();
+        // a non-ERC-20 contract answering with something else
+        mock.push::<Bytes, _>(Bytes::from(vec![0x01])).unwrap();
+        let oracle = ProviderGasOracle::<MockProvider>::new().with_provider("eth", provider);
+        let (token, holder) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+
+        assert!(oracle.token_balance_at("eth", token, holder, 100).await.is_err());
+        assert_eq!(oracle.token_balance_at("eth", token, holder, 100).await.unwrap(), U256::from(2_500_000u64));
+    }
+
+    #[test]
+    fn test_min_transfer_cost() {
+        assert_eq!(min_transfer_cost(U256::from(1_000_000_000u64)), U256::from(21_000_000_000_000u64));
+    }
+}
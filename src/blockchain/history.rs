@@ -0,0 +1,518 @@
+//! Historical transfers of an address, for backfilling imported wallets.
+//!
+//! A [`HistorySource`] answers one question: which native transactions and
+//! ERC-20 `Transfer` logs touched an address in a block range. Two strategies
+//! exist and both reduce what they read to [`RawTransfer`]s through the same
+//! constructors, so a transfer looks the same whichever one found it:
+//!
+//! - [`RpcHistory`] walks every block of the range over JSON-RPC (with full
+//!   transactions, plus one receipt per match for status and gas) and asks
+//!   for `Transfer` logs of the same range with the address as sender or
+//!   recipient topic. Calls go through the network's rate-limited provider.
+//! - [`ExplorerHistory`] reads an Etherscan-compatible API (`txlist` and
+//!   `getLogs`), which avoids scanning blocks. Its requests take tokens from
+//!   a bucket configured like an RPC endpoint's.
+//!
+//! Internal transactions (value moved by contract calls) are not covered by
+//! either strategy.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ethers::providers::Middleware;
+use ethers::types::{Address, Block, Filter, Log, Transaction, TransactionReceipt, H256, U256, U64};
+use ethers::utils::keccak256;
+use serde::Deserialize;
+
+use super::client_registry::ClientRegistry;
+use super::ethereum::provider_error;
+use super::rpc_limits::{self, EndpointLimiter, DEFAULT_USER_WAIT};
+use crate::core::errors::WalletError;
+
+/// Rows per explorer page
+const EXPLORER_PAGE_SIZE: usize = 1000;
+/// Etherscan-compatible APIs refuse `page * offset` beyond this
+const EXPLORER_MAX_RESULTS: usize = 10_000;
+
+/// `keccak256("Transfer(address,address,uint256)")`
+pub fn transfer_topic() -> H256 {
+    H256::from(keccak256("Transfer(address,address,uint256)"))
+}
+
+/// One movement of value that touched the scanned address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTransfer {
+    pub tx_hash: H256,
+    /// Index of the `Transfer` log in its block; `None` for the transaction
+    /// itself (its native value and gas)
+    pub log_index: Option<u64>,
+    pub block_number: u64,
+    /// Unix seconds of the block
+    pub timestamp: i64,
+    pub from: Address,
+    /// `None` for contract creations
+    pub to: Option<Address>,
+    /// ERC-20 contract; `None` for native value
+    pub token: Option<Address>,
+    pub value: U256,
+    /// Gas paid by `from` in wei; zero for token transfers
+    pub fee: U256,
+    /// Reverted transactions still pay gas but move no value
+    pub success: bool,
+}
+
+impl RawTransfer {
+    /// The transaction's own value and gas, from its receipt.
+    pub fn native(tx: &Transaction, receipt: &TransactionReceipt, block_number: u64, timestamp: i64) -> Self {
+        let gas_price = receipt.effective_gas_price.or(tx.gas_price).unwrap_or_default();
+        Self {
+            tx_hash: tx.hash,
+            log_index: None,
+            block_number,
+            timestamp,
+            from: tx.from,
+            to: tx.to,
+            token: None,
+            value: tx.value,
+            fee: receipt.gas_used.unwrap_or_default().saturating_mul(gas_price),
+            success: receipt.status != Some(U64::zero()),
+        }
+    }
+
+    /// An ERC-20 `Transfer` log; `None` for other events, ERC-721 transfers
+    /// (token id indexed, four topics), removed logs and logs missing their
+    /// position.
+    pub fn token_transfer(log: &Log, timestamp: i64) -> Option<Self> {
+        if log.removed == Some(true) || log.topics.len() != 3 || log.topics[0] != transfer_topic() {
+            return None;
+        }
+        if log.data.len() != 32 {
+            return None;
+        }
+        Some(Self {
+            tx_hash: log.transaction_hash?,
+            log_index: Some(log.log_index?.as_u64()),
+            block_number: log.block_number?.as_u64(),
+            timestamp,
+            from: Address::from(log.topics[1]),
+            to: Some(Address::from(log.topics[2])),
+            token: Some(log.address),
+            value: U256::from_big_endian(&log.data),
+            fee: U256::zero(),
+            success: true,
+        })
+    }
+}
+
+/// Where backfill reads history from
+#[async_trait]
+pub trait HistorySource: Send + Sync {
+    /// Recorded on the scan cursor: `rpc` or `explorer`
+    fn strategy(&self) -> &'static str;
+
+    /// Latest block height
+    async fn head(&self, network: &str) -> Result<u64, WalletError>;
+
+    /// Native transactions from or to `address` and ERC-20 transfers naming
+    /// it, in blocks `from..=to`, ordered by block and position. Fails rather
+    /// than return a partial range.
+    async fn transfers(
+        &self,
+        network: &str,
+        address: Address,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<RawTransfer>, WalletError>;
+}
+
+/// The JSON-RPC calls [`RpcHistory`] makes; abstracted so tests can script a chain.
+#[async_trait]
+pub trait HistoryRpc: Send + Sync {
+    async fn block_number(&self, network: &str) -> Result<u64, WalletError>;
+
+    /// Block `number` with full transactions; `None` if it does not exist yet.
+    async fn block_with_transactions(
+        &self,
+        network: &str,
+        number: u64,
+    ) -> Result<Option<Block<Transaction>>, WalletError>;
+
+    async fn transaction_receipt(
+        &self,
+        network: &str,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError>;
+
+    async fn logs(&self, network: &str, filter: &Filter) -> Result<Vec<Log>, WalletError>;
+}
+
+impl ClientRegistry {
+    fn history_provider(&self, network: &str) -> Result<&super::client_registry::LimitedProvider, WalletError> {
+        self.provider(network)
+            .ok_or_else(|| WalletError::NetworkError(format!("No RPC provider configured for network {}", network)))
+    }
+}
+
+#[async_trait]
+impl HistoryRpc for ClientRegistry {
+    async fn block_number(&self, network: &str) -> Result<u64, WalletError> {
+        let number = self
+            .history_provider(network)?
+            .get_block_number()
+            .await
+            .map_err(|e| provider_error("Failed to get block number", e))?;
+        Ok(number.as_u64())
+    }
+
+    async fn block_with_transactions(
+        &self,
+        network: &str,
+        number: u64,
+    ) -> Result<Option<Block<Transaction>>, WalletError> {
+        self.history_provider(network)?
+            .get_block_with_txs(number)
+            .await
+            .map_err(|e| provider_error(format!("Failed to get block {}", number), e))
+    }
+
+    async fn transaction_receipt(
+        &self,
+        network: &str,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        self.history_provider(network)?
+            .get_transaction_receipt(tx_hash)
+            .await
+            .map_err(|e| provider_error("Failed to get transaction receipt", e))
+    }
+
+    async fn logs(&self, network: &str, filter: &Filter) -> Result<Vec<Log>, WalletError> {
+        self.history_provider(network)?.get_logs(filter).await.map_err(|e| provider_error("Failed to get logs", e))
+    }
+}
+
+/// Scans blocks and `Transfer` logs over JSON-RPC.
+pub struct RpcHistory {
+    rpc: Arc<dyn HistoryRpc>,
+}
+
+impl RpcHistory {
+    pub fn new(rpc: Arc<dyn HistoryRpc>) -> Self {
+        Self { rpc }
+    }
+}
+
+/// Orders transfers by block, then the transaction before its logs.
+fn sort_transfers(transfers: &mut [RawTransfer]) {
+    transfers.sort_by_key(|t| (t.block_number, t.log_index.map_or(0, |i| i + 1), t.tx_hash));
+}
+
+#[async_trait]
+impl HistorySource for RpcHistory {
+    fn strategy(&self) -> &'static str {
+        "rpc"
+    }
+
+    async fn head(&self, network: &str) -> Result<u64, WalletError> {
+        self.rpc.block_number(network).await
+    }
+
+    async fn transfers(
+        &self,
+        network: &str,
+        address: Address,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<RawTransfer>, WalletError> {
+        let mut transfers = Vec::new();
+        let mut timestamps = HashMap::new();
+        for number in from..=to {
+            let block = self
+                .rpc
+                .block_with_transactions(network, number)
+                .await?
+                .ok_or_else(|| WalletError::BlockchainError(format!("Block {} is not available", number)))?;
+            let timestamp = block.timestamp.low_u64() as i64;
+            timestamps.insert(number, timestamp);
+            for tx in block.transactions.iter().filter(|tx| tx.from == address || tx.to == Some(address)) {
+                let receipt = self.rpc.transaction_receipt(network, tx.hash).await?.ok_or_else(|| {
+                    WalletError::BlockchainError(format!("No receipt for mined transaction {:?}", tx.hash))
+                })?;
+                transfers.push(RawTransfer::native(tx, &receipt, number, timestamp));
+            }
+        }
+
+        // sent and received are two queries; a transfer to self comes back from both
+        let topic = H256::from(address);
+        let sent = Filter::new().from_block(from).to_block(to).topic0(transfer_topic()).topic1(topic);
+        let received = Filter::new().from_block(from).to_block(to).topic0(transfer_topic()).topic2(topic);
+        let mut logs = BTreeMap::new();
+        for filter in [sent, received] {
+            for log in self.rpc.logs(network, &filter).await? {
+                if let (Some(hash), Some(index)) = (log.transaction_hash, log.log_index) {
+                    logs.insert((hash, index), log);
+                }
+            }
+        }
+        for log in logs.values() {
+            let timestamp = log.block_number.and_then(|n| timestamps.get(&n.as_u64())).copied().unwrap_or_default();
+            transfers.extend(RawTransfer::token_transfer(log, timestamp));
+        }
+        sort_transfers(&mut transfers);
+        Ok(transfers)
+    }
+}
+
+/// Envelope of every Etherscan-compatible response
+#[derive(Debug, Deserialize)]
+struct ExplorerResponse {
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    result: serde_json::Value,
+}
+
+/// `module=account&action=txlist` row; numbers are decimal strings
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExplorerTransaction {
+    block_number: String,
+    time_stamp: String,
+    hash: H256,
+    from: Address,
+    #[serde(default)]
+    to: String,
+    value: String,
+    gas_price: String,
+    gas_used: String,
+    #[serde(default)]
+    is_error: String,
+}
+
+/// `module=logs&action=getLogs` row; numbers are hex strings
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExplorerLog {
+    address: Address,
+    topics: Vec<H256>,
+    data: ethers::types::Bytes,
+    block_number: String,
+    time_stamp: String,
+    log_index: String,
+    transaction_hash: H256,
+}
+
+fn malformed(field: &str, value: &str) -> WalletError {
+    WalletError::BlockchainError(format!("Explorer returned {} {:?}", field, value))
+}
+
+fn decimal(field: &str, value: &str) -> Result<U256, WalletError> {
+    U256::from_dec_str(value).map_err(|_| malformed(field, value))
+}
+
+/// Hex quantity; some explorers send `0x` for zero.
+fn hex_quantity(field: &str, value: &str) -> Result<u64, WalletError> {
+    let digits = value.trim_start_matches("0x");
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(digits, 16).map_err(|_| malformed(field, value))
+}
+
+impl ExplorerTransaction {
+    fn into_transfer(self) -> Result<RawTransfer, WalletError> {
+        let to = if self.to.is_empty() {
+            None
+        } else {
+            Some(self.to.parse().map_err(|_| malformed("to", &self.to))?)
+        };
+        Ok(RawTransfer {
+            tx_hash: self.hash,
+            log_index: None,
+            block_number: decimal("blockNumber", &self.block_number)?.low_u64(),
+            timestamp: decimal("timeStamp", &self.time_stamp)?.low_u64() as i64,
+            from: self.from,
+            to,
+            token: None,
+            value: decimal("value", &self.value)?,
+            fee: decimal("gasUsed", &self.gas_used)?.saturating_mul(decimal("gasPrice", &self.gas_price)?),
+            success: self.is_error != "1",
+        })
+    }
+}
+
+impl ExplorerLog {
+    fn into_transfer(self) -> Result<Option<RawTransfer>, WalletError> {
+        let timestamp = hex_quantity("timeStamp", &self.time_stamp)? as i64;
+        let log = Log {
+            address: self.address,
+            topics: self.topics,
+            data: self.data,
+            block_number: Some(U64::from(hex_quantity("blockNumber", &self.block_number)?)),
+            transaction_hash: Some(self.transaction_hash),
+            log_index: Some(U256::from(hex_quantity("logIndex", &self.log_index)?)),
+            ..Default::default()
+        };
+        Ok(RawTransfer::token_transfer(&log, timestamp))
+    }
+}
+
+/// Reads an Etherscan-compatible explorer API.
+pub struct ExplorerHistory {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+    limiter: Option<Arc<EndpointLimiter>>,
+}
+
+impl ExplorerHistory {
+    /// `url` is the API endpoint, e.g. `https://api.etherscan.io/api`.
+    pub fn new(url: impl Into<String>, api_key: Option<String>, limiter: Option<Arc<EndpointLimiter>>) -> Self {
+        Self { client: reqwest::Client::new(), url: url.into(), api_key, limiter }
+    }
+
+    /// One request; takes a token first, like an RPC call would.
+    async fn get(&self, params: &[(&str, String)]) -> Result<serde_json::Value, WalletError> {
+        rpc_limits::acquire(std::slice::from_ref(&self.limiter), DEFAULT_USER_WAIT)
+            .await
+            .map_err(|e| WalletError::NetworkBusy(format!("Explorer request: {}", e)))?;
+        let mut request = self.client.get(&self.url).query(params);
+        if let Some(key) = &self.api_key {
+            request = request.query(&[("apikey", key)]);
+        }
+        let response: ExplorerResponse = request
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| WalletError::NetworkError(format!("Explorer request failed: {}", e.without_url())))?
+            .json()
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Explorer response unreadable: {}", e.without_url())))?;
+        match (response.status.as_deref(), response.result) {
+            // "No transactions found" / "No records found" come back as status 0 with an empty list
+            (Some("0"), serde_json::Value::Array(rows)) if rows.is_empty() => Ok(serde_json::Value::Array(rows)),
+            (Some("0"), result) => Err(WalletError::BlockchainError(format!(
+                "Explorer error: {} ({})",
+                response.message.unwrap_or_default(),
+                result
+            ))),
+            (_, result) => Ok(result),
+        }
+    }
+
+    /// Every row of a paged listing.
+    async fn rows<T: serde::de::DeserializeOwned>(&self, params: &[(&str, String)]) -> Result<Vec<T>, WalletError> {
+        let mut rows = Vec::new();
+        for page in 1.. {
+            let mut paged = params.to_vec();
+            paged.push(("page", page.to_string()));
+            paged.push(("offset", EXPLORER_PAGE_SIZE.to_string()));
+            let batch: Vec<T> = serde_json::from_value(self.get(&paged).await?)
+                .map_err(|e| WalletError::BlockchainError(format!("Explorer response unreadable: {}", e)))?;
+            let full = batch.len() == EXPLORER_PAGE_SIZE;
+            rows.extend(batch);
+            if !full {
+                return Ok(rows);
+            }
+            if page * EXPLORER_PAGE_SIZE >= EXPLORER_MAX_RESULTS {
+                return Err(WalletError::BlockchainError(format!(
+                    "More than {} explorer results in one range; lower backfill.blocks_per_batch",
+                    EXPLORER_MAX_RESULTS
+                )));
+            }
+        }
+        Ok(rows)
+    }
+}
+
+#[async_trait]
+impl HistorySource for ExplorerHistory {
+    fn strategy(&self) -> &'static str {
+        "explorer"
+    }
+
+    async fn head(&self, _network: &str) -> Result<u64, WalletError> {
+        let result = self.get(&[("module", "proxy".to_string()), ("action", "eth_blockNumber".to_string())]).await?;
+        hex_quantity("eth_blockNumber", result.as_str().unwrap_or_default())
+    }
+
+    async fn transfers(
+        &self,
+        _network: &str,
+        address: Address,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<RawTransfer>, WalletError> {
+        let address_param = format!("{:?}", address);
+        let txs: Vec<ExplorerTransaction> = self
+            .rows(&[
+                ("module", "account".to_string()),
+                ("action", "txlist".to_string()),
+                ("address", address_param),
+                ("startblock", from.to_string()),
+                ("endblock", to.to_string()),
+                ("sort", "asc".to_string()),
+            ])
+            .await?;
+        let mut transfers = txs.into_iter().map(ExplorerTransaction::into_transfer).collect::<Result<Vec<_>, _>>()?;
+
+        let topic = format!("{:?}", H256::from(address));
+        let mut logs = BTreeMap::new();
+        for (position, operator) in [("topic1", "topic0_1_opr"), ("topic2", "topic0_2_opr")] {
+            let rows: Vec<ExplorerLog> = self
+                .rows(&[
+                    ("module", "logs".to_string()),
+                    ("action", "getLogs".to_string()),
+                    ("fromBlock", from.to_string()),
+                    ("toBlock", to.to_string()),
+                    ("topic0", format!("{:?}", transfer_topic())),
+                    (position, topic.clone()),
+                    (operator, "and".to_string()),
+                ])
+                .await?;
+            for row in rows {
+                if let Some(transfer) = row.into_transfer()? {
+                    logs.insert((transfer.tx_hash, transfer.log_index), transfer);
+                }
+            }
+        }
+        transfers.extend(logs.into_values());
+        sort_transfers(&mut transfers);
+        Ok(transfers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explorer_rows_normalize_like_rpc_ones() {
+        let wallet: Address = "0x00000000000000000000000000000000000000aa".parse().unwrap();
+        let token: Address = "0x00000000000000000000000000000000000000cc".parse().unwrap();
+        let tx: ExplorerTransaction = serde_json::from_value(serde_json::json!({
+            "blockNumber": "12", "timeStamp": "1700000000",
+            "hash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "from": "0x00000000000000000000000000000000000000aa", "to": "",
+            "value": "1000", "gasPrice": "2", "gasUsed": "21000", "isError": "1"
+        }))
+        .unwrap();
+        let transfer = tx.into_transfer().unwrap();
+        assert_eq!((transfer.to, transfer.success, transfer.fee), (None, false, U256::from(42_000u64)));
+
+        // Etherscan reports log index 0 as "0x"
+        let log: ExplorerLog = serde_json::from_value(serde_json::json!({
+            "address": token, "data": format!("0x{:064x}", 5),
+            "topics": [transfer_topic(), H256::from(Address::zero()), H256::from(wallet)],
+            "blockNumber": "0xc", "timeStamp": "0x6553f100", "logIndex": "0x",
+            "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000002"
+        }))
+        .unwrap();
+        let transfer = log.into_transfer().unwrap().unwrap();
+        assert_eq!(transfer.log_index, Some(0));
+        assert_eq!((transfer.block_number, transfer.timestamp), (12, 1_700_000_000));
+        assert_eq!((transfer.to, transfer.token, transfer.value), (Some(wallet), Some(token), U256::from(5)));
+    }
+}
@@ -1,7 +1,18 @@
 pub mod audit;
 pub mod bridge;
+pub mod circuit_breaker;
+pub mod client_registry;
+pub mod erc20;
 pub mod ethereum;
+pub mod failover;
+pub mod gas_oracle;
+pub mod history;
+pub mod recipient_guard;
+pub mod rpc_limits;
 pub mod traits; // Added minimal stub for audit module
+pub mod tx_inspect;
+pub mod tx_status_hub;
+pub mod tx_watch;
 
 #[cfg(feature = "bitcoin")]
 pub mod bitcoin;
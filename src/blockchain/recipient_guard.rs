@@ -0,0 +1,292 @@
+//! Pre-send recipient inspection for EVM transfers.
+//!
+//! Native coins or tokens sent to a token contract (USDC to the USDC contract)
+//! are usually gone for good. Before a plain transfer is signed, the
+//! recipient's code is looked up; a contract that is not on the network's
+//! interaction allowlist (routers, bridges we call on purpose) needs an
+//! explicit acknowledgement. Contract calls are never inspected: there the
+//! recipient being a contract is the point.
+//!
+//! `eth_getCode` results are cached per network and address for the configured
+//! TTL, so frequent recipients cost one RPC per TTL rather than one per send.
+//! A lookup that fails (no provider, RPC error) lets the send through as
+//! `unchecked`: the guard catches mistakes, it is not an authorization check.
+
+use ethers::abi::{self, ParamType, Token};
+use ethers::types::Address;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use super::gas_oracle::GasOracle;
+use crate::core::abi::selector_from_signature;
+use crate::core::config::RecipientGuardConfig;
+use crate::core::errors::WalletError;
+
+/// Cached code lookups kept before expired entries are swept
+const MAX_CACHED_CODES: usize = 10_000;
+
+/// What the recipient of a transfer turned out to be
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecipientClass {
+    /// No code: a regular account
+    ExternallyOwned,
+    /// A token contract from the configured registry
+    TokenContract { symbol: String },
+    /// A contract on the network's interaction allowlist
+    ProtocolContract,
+    /// Any other contract
+    UnknownContract,
+    /// Guard disabled or the lookup failed
+    Unchecked,
+}
+
+impl RecipientClass {
+    /// Whether a plain transfer to this recipient must be acknowledged.
+    pub fn requires_acknowledgement(&self) -> bool {
+        matches!(self, RecipientClass::TokenContract { .. } | RecipientClass::UnknownContract)
+    }
+
+    /// Anomaly-detection feature in `[0, 1]`: token contracts are the classic
+    /// loss pattern, unknown contracts are merely suspicious.
+    pub fn risk(&self) -> f64 {
+        match self {
+            RecipientClass::TokenContract { .. } => 1.0,
+            RecipientClass::UnknownContract => 0.6,
+            RecipientClass::ExternallyOwned | RecipientClass::ProtocolContract | RecipientClass::Unchecked => 0.0,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            RecipientClass::ExternallyOwned => "externally owned account".to_string(),
+            RecipientClass::TokenContract { symbol } => format!("the {} token contract", symbol),
+            RecipientClass::ProtocolContract => "an allowlisted protocol contract".to_string(),
+            RecipientClass::UnknownContract => "an unknown contract".to_string(),
+            RecipientClass::Unchecked => "not checked".to_string(),
+        }
+    }
+}
+
+/// A plain transfer to a contract that was not acknowledged
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Recipient {recipient:?} is {}; set acknowledge_contract_recipient to send anyway", class.describe())]
+pub struct ContractRecipient {
+    pub recipient: Address,
+    pub class: RecipientClass,
+}
+
+impl ContractRecipient {
+    pub fn code(&self) -> &'static str {
+        "RECIPIENT_IS_CONTRACT"
+    }
+}
+
+struct CodeEntry {
+    has_code: bool,
+    fetched_at: Instant,
+}
+
+/// `(network, address)`
+type Key = (String, Address);
+
+pub struct RecipientGuard {
+    enabled: bool,
+    ttl: Duration,
+    tokens: HashMap<Key, String>,
+    allowlist: HashSet<Key>,
+    codes: Mutex<HashMap<Key, CodeEntry>>,
+}
+
+impl RecipientGuard {
+    /// Registry and allowlist entries that are not addresses are skipped with
+    /// a warning; a skipped allowlist entry only means sends to it need
+    /// acknowledging.
+    pub fn from_config(config: &RecipientGuardConfig) -> Self {
+        let parse = |section: &str, network: &str, address: &str| match address.trim().parse::<Address>() {
+            Ok(address) => Some((network.to_string(), address)),
+            Err(e) => {
+                tracing::warn!("recipient guard: skipping {} entry {} on {} ({})", section, address, network, e);
+                None
+            }
+        };
+        let tokens = config
+            .token_contracts
+            .iter()
+            .flat_map(|(network, tokens)| tokens.iter().map(move |(symbol, address)| (network, symbol, address)))
+            .filter_map(|(network, symbol, address)| {
+                parse("token_contracts", network, address).map(|key| (key, symbol.clone()))
+            })
+            .collect();
+        let allowlist = config
+            .interaction_allowlist
+            .iter()
+            .flat_map(|(network, addresses)| addresses.iter().map(move |address| (network, address)))
+            .filter_map(|(network, address)| parse("interaction_allowlist", network, address))
+            .collect();
+        Self {
+            enabled: config.enabled,
+            ttl: Duration::from_secs(config.code_cache_ttl_secs),
+            tokens,
+            allowlist,
+            codes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Classifies `recipient` on `network`. Allowlisted addresses need no RPC.
+    pub async fn classify(&self, oracle: &dyn GasOracle, network: &str, recipient: Address) -> RecipientClass {
+        if !self.enabled {
+            return RecipientClass::Unchecked;
+        }
+        let key = (network.to_string(), recipient);
+        if self.allowlist.contains(&key) {
+            return RecipientClass::ProtocolContract;
+        }
+        match self.has_code(oracle, &key).await {
+            Ok(false) => RecipientClass::ExternallyOwned,
+            Ok(true) => match self.tokens.get(&key) {
+                Some(symbol) => RecipientClass::TokenContract { symbol: symbol.clone() },
+                None => RecipientClass::UnknownContract,
+            },
+            Err(e) => {
+                tracing::warn!("recipient guard: code lookup of {:?} on {} failed: {}", recipient, network, e);
+                RecipientClass::Unchecked
+            }
+        }
+    }
+
+    /// Classifies `recipient` of a plain transfer and refuses contracts that
+    /// need acknowledging unless `acknowledged`.
+    pub async fn check(
+        &self,
+        oracle: &dyn GasOracle,
+        network: &str,
+        recipient: Address,
+        acknowledged: bool,
+    ) -> Result<RecipientClass, ContractRecipient> {
+        let class = self.classify(oracle, network, recipient).await;
+        if class.requires_acknowledgement() && !acknowledged {
+            return Err(ContractRecipient { recipient, class });
+        }
+        Ok(class)
+    }
+
+    async fn has_code(&self, oracle: &dyn GasOracle, key: &Key) -> Result<bool, WalletError> {
+        if let Some(entry) = self.codes.lock().get(key) {
+            if entry.fetched_at.elapsed() < self.ttl {
+                return Ok(entry.has_code);
+            }
+        }
+        if !oracle.supports(&key.0) {
+            return Err(WalletError::NetworkError(format!("No provider for network {}", key.0)));
+        }
+        let has_code = !oracle.code_at(&key.0, key.1).await?.is_empty();
+
+        let mut codes = self.codes.lock();
+        if codes.len() >= MAX_CACHED_CODES {
+            let ttl = self.ttl;
+            codes.retain(|_, entry| entry.fetched_at.elapsed() < ttl);
+            if codes.len() >= MAX_CACHED_CODES {
+                codes.clear();
+            }
+        }
+        codes.insert(key.clone(), CodeEntry { has_code, fetched_at: Instant::now() });
+        Ok(has_code)
+    }
+}
+
+/// Who receives the value of a transaction that is a plain transfer: `to`
+/// for a native transfer (no calldata), the `to` argument of an ERC-20
+/// `transfer`. `None` for contract creation and any other contract call.
+pub fn plain_transfer_recipient(to: Option<Address>, data: &[u8]) -> Option<Address> {
+    let to = to?;
+    if data.is_empty() {
+        return Some(to);
+    }
+    let (selector, args) = data.split_at(data.len().min(4));
+    if selector != selector_from_signature("transfer(address,uint256)") {
+        return None;
+    }
+    match abi::decode(&[ParamType::Address, ParamType::Uint(256)], args).ok()?.first() {
+        Some(Token::Address(recipient)) => Some(*recipient),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::gas_oracle::ProviderGasOracle;
+    use ethers::providers::{MockProvider, Provider};
+    use ethers::types::{Bytes, U256};
+
+    const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+    fn oracle(codes: &[&[u8]]) -> ProviderGasOracle<MockProvider> {
+        let (provider, mock) = Provider::mocked();
+        for code in codes {
+            mock.push::<Bytes, _>(Bytes::from(code.to_vec())).unwrap();
+        }
+        ProviderGasOracle::<MockProvider>::new().with_provider("eth", provider)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_code_lookups_are_cached_until_ttl() {
+        let guard = RecipientGuard::from_config(&RecipientGuardConfig::default());
+        let usdc: Address = USDC.parse().unwrap();
+        // MockProvider answers LIFO: USDC's code first, empty code after the TTL
+        let oracle = oracle(&[&[], &[0x60, 0x80]]);
+
+        let token = RecipientClass::TokenContract { symbol: "USDC".to_string() };
+        assert_eq!(guard.classify(&oracle, "eth", usdc).await, token);
+        assert_eq!(guard.classify(&oracle, "eth", usdc).await, token);
+
+        tokio::time::advance(Duration::from_secs(601)).await;
+        assert_eq!(guard.classify(&oracle, "eth", usdc).await, RecipientClass::ExternallyOwned);
+        // no responses left: a failed lookup lets the send through
+        tokio::time::advance(Duration::from_secs(601)).await;
+        assert_eq!(guard.classify(&oracle, "eth", usdc).await, RecipientClass::Unchecked);
+    }
+
+    #[tokio::test]
+    async fn test_check_requires_acknowledgement_for_contracts() {
+        let config = RecipientGuardConfig {
+            interaction_allowlist: HashMap::from([("eth".to_string(), vec![USDC.to_lowercase()])]),
+            ..Default::default()
+        };
+        let guard = RecipientGuard::from_config(&config);
+        let unknown = Address::repeat_byte(0x11);
+        let oracle = oracle(&[&[0x60, 0x80]]);
+
+        let err = guard.check(&oracle, "eth", unknown, false).await.unwrap_err();
+        assert_eq!(err.class, RecipientClass::UnknownContract);
+        assert_eq!(err.code(), "RECIPIENT_IS_CONTRACT");
+        assert_eq!(guard.check(&oracle, "eth", unknown, true).await.unwrap(), RecipientClass::UnknownContract);
+        // the allowlist wins over the token registry and needs no RPC
+        let usdc = USDC.parse().unwrap();
+        assert_eq!(guard.check(&oracle, "eth", usdc, false).await.unwrap(), RecipientClass::ProtocolContract);
+
+        let disabled = RecipientGuard::from_config(&RecipientGuardConfig { enabled: false, ..Default::default() });
+        assert_eq!(disabled.check(&oracle, "eth", unknown, false).await.unwrap(), RecipientClass::Unchecked);
+    }
+
+    #[test]
+    fn test_plain_transfer_recipient() {
+        let to = Address::repeat_byte(0x22);
+        let recipient = Address::repeat_byte(0x33);
+        assert_eq!(plain_transfer_recipient(Some(to), &[]), Some(to));
+        assert_eq!(plain_transfer_recipient(None, &[]), None);
+
+        let mut transfer = selector_from_signature("transfer(address,uint256)").to_vec();
+        transfer.extend(abi::encode(&[Token::Address(recipient), Token::Uint(U256::from(5u64))]));
+        assert_eq!(plain_transfer_recipient(Some(to), &transfer), Some(recipient));
+
+        let mut approve = selector_from_signature("approve(address,uint256)").to_vec();
+        approve.extend(abi::encode(&[Token::Address(recipient), Token::Uint(U256::from(5u64))]));
+        assert_eq!(plain_transfer_recipient(Some(to), &approve), None);
+        assert_eq!(plain_transfer_recipient(Some(to), &transfer[..10]), None);
+    }
+}
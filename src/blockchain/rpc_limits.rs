@@ -0,0 +1,464 @@
+//! Outbound RPC quotas.
+//!
+//! Every RPC endpoint configured with a [`RpcRateLimit`] gets one
+//! [`EndpointLimiter`]: a per-second token bucket plus, when a daily quota is
+//! set, a bucket that refills over a day. A call spends one token from each.
+//! [`RpcLimiters`] keys limiters by URL, so networks pointed at the same
+//! provider URL draw from the same quota, and every client built from the
+//! registry shares them through an `Arc`.
+//!
+//! What happens when no endpoint has a token depends on the caller's
+//! [`CallerClass`], carried in a task-local:
+//! - `User` waits up to a small budget, then fails with `NETWORK_BUSY`;
+//! - `Background` waits until a token frees up or its job is cancelled, and
+//!   steps aside while a user call is waiting on the same endpoint;
+//! - `Probe` does not wait; the call is skipped.
+//!
+//! Buckets run on `tokio::time::Instant`, so tests drive them with a paused
+//! runtime clock.
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::providers::{JsonRpcError, ProviderError, RpcError};
+use parking_lot::Mutex;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use crate::core::config::RpcRateLimit;
+use crate::monitoring::WalletMetrics;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// How long a user-facing call waits for a token before `NETWORK_BUSY`
+pub const DEFAULT_USER_WAIT: Duration = Duration::from_millis(250);
+
+/// How soon a background call that stepped aside for a user checks again
+const BACKGROUND_RECHECK: Duration = Duration::from_millis(50);
+
+/// Slack for float error in the refill arithmetic
+const TOKEN_EPSILON: f64 = 1e-9;
+
+/// Who is making an RPC call; decides what happens when the quota is exhausted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallerClass {
+    /// API request handling: waits up to a small budget, then `NETWORK_BUSY`.
+    User,
+    /// Scheduled jobs: waits as long as it takes unless cancelled, and lets a
+    /// waiting user call take the next token.
+    Background,
+    /// Health checks: skipped when no token is available.
+    Probe,
+}
+
+#[derive(Clone)]
+struct CallerContext {
+    class: CallerClass,
+    cancel: Option<CancellationToken>,
+}
+
+tokio::task_local! {
+    static CALLER: CallerContext;
+}
+
+impl CallerClass {
+    /// Class of the running task; calls made outside any scope are user-facing
+    pub fn current() -> Self {
+        CALLER.try_with(|caller| caller.class).unwrap_or(CallerClass::User)
+    }
+
+    /// Runs `fut` with the RPC calls it makes counted as this class
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        CALLER.scope(CallerContext { class: self, cancel: None }, fut).await
+    }
+}
+
+/// Runs a background job; its calls stop waiting for quota once `cancel` fires
+pub async fn background<F: Future>(cancel: CancellationToken, fut: F) -> F::Output {
+    CALLER.scope(CallerContext { class: CallerClass::Background, cancel: Some(cancel) }, fut).await
+}
+
+fn current_cancel() -> Option<CancellationToken> {
+    CALLER.try_with(|caller| caller.cancel.clone()).ok().flatten()
+}
+
+/// Why an RPC call was not sent
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RpcLimitError {
+    #[error("NETWORK_BUSY: request quota of {0} exhausted")]
+    Busy(String),
+    #[error("request quota of {0} exhausted; probe skipped")]
+    Skipped(String),
+    #[error("cancelled while waiting for request quota of {0}")]
+    Cancelled(String),
+}
+
+impl RpcError for RpcLimitError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        None
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        None
+    }
+}
+
+impl From<RpcLimitError> for ProviderError {
+    fn from(e: RpcLimitError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(e))
+    }
+}
+
+/// The quota error behind a provider failure, i.e. the call never left this process
+pub fn quota_error(e: &ProviderError) -> Option<&RpcLimitError> {
+    match e {
+        ProviderError::JsonRpcClientError(inner) => {
+            let inner: &(dyn std::error::Error + 'static) = inner.as_ref();
+            inner.downcast_ref()
+        }
+        _ => None,
+    }
+}
+
+/// Holds up to `capacity` tokens, refilled continuously at `rate` per second
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket
+    pub fn new(capacity: f64, rate: f64, now: Instant) -> Self {
+        Self { capacity, rate, tokens: capacity, refilled_at: now }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.refilled_at = self.refilled_at.max(now);
+    }
+
+    pub fn available(&mut self, now: Instant) -> f64 {
+        self.refill(now);
+        self.tokens
+    }
+
+    /// How long after `now` until a whole token is available
+    pub fn time_to_token(&mut self, now: Instant) -> Duration {
+        let missing = 1.0 - self.available(now);
+        if missing <= TOKEN_EPSILON {
+            return Duration::ZERO;
+        }
+        Duration::from_micros((missing / self.rate * 1e6).ceil() as u64)
+    }
+
+    /// Spends a token if one is available
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        if !self.time_to_token(now).is_zero() {
+            return false;
+        }
+        self.tokens = (self.tokens - 1.0).max(0.0);
+        true
+    }
+
+    /// Share of the capacity spent (0 = full bucket, 1 = empty)
+    pub fn utilization(&mut self, now: Instant) -> f64 {
+        1.0 - self.available(now) / self.capacity
+    }
+}
+
+struct Buckets {
+    second: TokenBucket,
+    day: Option<TokenBucket>,
+}
+
+impl Buckets {
+    fn time_to_token(&mut self, now: Instant) -> Duration {
+        let day = self.day.as_mut().map_or(Duration::ZERO, |day| day.time_to_token(now));
+        self.second.time_to_token(now).max(day)
+    }
+}
+
+/// Current utilization of one endpoint's buckets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketUtilization {
+    pub second: f64,
+    pub day: Option<f64>,
+}
+
+/// The token buckets of one RPC endpoint
+pub struct EndpointLimiter {
+    label: String,
+    buckets: Mutex<Buckets>,
+    users_waiting: AtomicUsize,
+    metrics: Option<Arc<WalletMetrics>>,
+}
+
+impl std::fmt::Debug for EndpointLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EndpointLimiter").field("label", &self.label).finish_non_exhaustive()
+    }
+}
+
+impl EndpointLimiter {
+    /// `label` names the endpoint in logs and gauges; it must not carry the
+    /// URL's path or query, which often hold the provider API key.
+    pub fn new(label: impl Into<String>, limit: &RpcRateLimit) -> Self {
+        let now = Instant::now();
+        let per_second = f64::from(limit.requests_per_second);
+        let burst = f64::from(limit.burst.unwrap_or(limit.requests_per_second));
+        let day = limit.requests_per_day.map(|n| TokenBucket::new(n as f64, n as f64 / SECONDS_PER_DAY, now));
+        Self {
+            label: label.into(),
+            buckets: Mutex::new(Buckets { second: TokenBucket::new(burst, per_second, now), day }),
+            users_waiting: AtomicUsize::new(0),
+            metrics: None,
+        }
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<WalletMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Spends a token for a `class` caller, or says how long to wait before
+    /// trying again. Background callers also wait while a user call is queued
+    /// here, so the user gets the next token.
+    pub fn try_acquire(&self, class: CallerClass) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock();
+        let wait = buckets.time_to_token(now);
+        let result = if !wait.is_zero() {
+            Err(wait)
+        } else if class == CallerClass::Background && self.users_waiting.load(Ordering::SeqCst) > 0 {
+            Err(BACKGROUND_RECHECK)
+        } else {
+            buckets.second.try_take(now);
+            if let Some(day) = buckets.day.as_mut() {
+                day.try_take(now);
+            }
+            Ok(())
+        };
+        self.publish(&mut buckets, now);
+        result
+    }
+
+    pub fn utilization(&self) -> BucketUtilization {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock();
+        BucketUtilization {
+            second: buckets.second.utilization(now),
+            day: buckets.day.as_mut().map(|day| day.utilization(now)),
+        }
+    }
+
+    fn publish(&self, buckets: &mut Buckets, now: Instant) {
+        let Some(metrics) = &self.metrics else { return };
+        metrics.set_rpc_bucket_utilization(&self.label, "second", buckets.second.utilization(now));
+        if let Some(day) = buckets.day.as_mut() {
+            metrics.set_rpc_bucket_utilization(&self.label, "day", day.utilization(now));
+        }
+    }
+}
+
+/// Marks a user call as queued on a set of endpoints until dropped
+struct UsersWaiting(Vec<Arc<EndpointLimiter>>);
+
+impl UsersWaiting {
+    fn register(limiters: &[Option<Arc<EndpointLimiter>>]) -> Self {
+        let limiters: Vec<_> = limiters.iter().flatten().cloned().collect();
+        for limiter in &limiters {
+            limiter.users_waiting.fetch_add(1, Ordering::SeqCst);
+        }
+        Self(limiters)
+    }
+}
+
+impl Drop for UsersWaiting {
+    fn drop(&mut self) {
+        for limiter in &self.0 {
+            limiter.users_waiting.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Picks the first endpoint, in priority order, that can take a call now
+/// (`None` entries are unlimited). When none can, the current
+/// [`CallerClass`] decides whether to wait, fail or skip; a waiting caller
+/// takes whichever endpoint frees up first.
+pub async fn acquire(limiters: &[Option<Arc<EndpointLimiter>>], user_wait: Duration) -> Result<usize, RpcLimitError> {
+    let class = CallerClass::current();
+    let deadline = Instant::now() + user_wait;
+    let mut queued: Option<UsersWaiting> = None;
+    loop {
+        let mut soonest: Option<Duration> = None;
+        for (index, limiter) in limiters.iter().enumerate() {
+            let Some(limiter) = limiter else { return Ok(index) };
+            match limiter.try_acquire(class) {
+                Ok(()) => return Ok(index),
+                Err(wait) => soonest = Some(soonest.map_or(wait, |soonest| soonest.min(wait))),
+            }
+        }
+        let primary = limiters.iter().flatten().next().map_or("rpc", |limiter| limiter.label()).to_string();
+        let Some(wait) = soonest else { return Err(RpcLimitError::Busy(primary)) };
+        match class {
+            CallerClass::Probe => return Err(RpcLimitError::Skipped(primary)),
+            CallerClass::User => {
+                if Instant::now() + wait > deadline {
+                    return Err(RpcLimitError::Busy(primary));
+                }
+                queued.get_or_insert_with(|| UsersWaiting::register(limiters));
+                tokio::time::sleep(wait).await;
+            }
+            CallerClass::Background => match current_cancel() {
+                Some(cancel) => tokio::select! {
+                    _ = cancel.cancelled() => return Err(RpcLimitError::Cancelled(primary)),
+                    _ = tokio::time::sleep(wait) => {}
+                },
+                None => tokio::time::sleep(wait).await,
+            },
+        }
+    }
+}
+
+/// Endpoint label for logs and gauges: host and port only
+pub fn endpoint_label(url: &str) -> String {
+    reqwest::Url::parse(url.trim())
+        .ok()
+        .and_then(|url| {
+            let host = url.host_str()?.to_string();
+            Some(match url.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host,
+            })
+        })
+        .unwrap_or_else(|| "invalid-url".to_string())
+}
+
+/// Endpoint limiters by URL
+#[derive(Clone, Default)]
+pub struct RpcLimiters {
+    limiters: HashMap<String, Arc<EndpointLimiter>>,
+    metrics: Option<Arc<WalletMetrics>>,
+}
+
+impl RpcLimiters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<WalletMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// The limiter for `url`, created on first use. A URL configured twice
+    /// keeps the limit it was first seen with.
+    pub fn endpoint(&mut self, url: &str, limit: &RpcRateLimit) -> Arc<EndpointLimiter> {
+        let key = url.trim().to_string();
+        if let Some(limiter) = self.limiters.get(&key) {
+            return limiter.clone();
+        }
+        // Same host with different keys in the path still gets its own gauge
+        let mut label = endpoint_label(&key);
+        if self.limiters.values().any(|limiter| limiter.label == label) {
+            label = format!("{}#{}", label, self.limiters.len() + 1);
+        }
+        let mut limiter = EndpointLimiter::new(label, limit);
+        if let Some(metrics) = &self.metrics {
+            limiter = limiter.with_metrics(metrics.clone());
+        }
+        let limiter = Arc::new(limiter);
+        self.limiters.insert(key, limiter.clone());
+        limiter
+    }
+
+    /// Utilization of every limited endpoint, by label
+    pub fn utilization(&self) -> BTreeMap<String, BucketUtilization> {
+        self.limiters.values().map(|limiter| (limiter.label.clone(), limiter.utilization())).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(per_second: u32, burst: Option<u32>, per_day: Option<u64>) -> RpcRateLimit {
+        RpcRateLimit { requests_per_second: per_second, burst, requests_per_day: per_day }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_bucket_refills_linearly_up_to_capacity() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(4.0, 2.0, start);
+        for _ in 0..4 {
+            assert!(bucket.try_take(start));
+        }
+        assert!(!bucket.try_take(start));
+        assert_eq!(bucket.time_to_token(start), Duration::from_millis(500));
+        assert_eq!(bucket.utilization(start), 1.0);
+
+        assert_eq!(bucket.available(start + Duration::from_millis(250)), 0.5);
+        assert_eq!(bucket.time_to_token(start + Duration::from_millis(250)), Duration::from_millis(250));
+        assert_eq!(bucket.available(start + Duration::from_secs(1)), 2.0);
+        assert_eq!(bucket.utilization(start + Duration::from_secs(1)), 0.5);
+        // never above capacity
+        assert_eq!(bucket.available(start + Duration::from_secs(60)), 4.0);
+        assert!(bucket.try_take(start + Duration::from_secs(60)));
+        assert_eq!(bucket.available(start + Duration::from_secs(60)), 3.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_daily_bucket_caps_calls_the_second_bucket_would_allow() {
+        let limiter = EndpointLimiter::new("node", &limit(4, None, Some(3)));
+        for _ in 0..3 {
+            assert_eq!(limiter.try_acquire(CallerClass::User), Ok(()));
+        }
+        // the per-second bucket still has a token, but the day is spent: one token per 86400 / 3 s
+        let wait = limiter.try_acquire(CallerClass::User).unwrap_err();
+        assert!(wait >= Duration::from_secs(28_800) && wait < Duration::from_millis(28_800_001), "{:?}", wait);
+        assert_eq!(limiter.utilization(), BucketUtilization { second: 0.75, day: Some(1.0) });
+
+        tokio::time::advance(wait).await;
+        assert_eq!(limiter.try_acquire(CallerClass::User), Ok(()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_gauges_follow_bucket_state() {
+        let metrics = Arc::new(WalletMetrics::new().unwrap());
+        let mut limiters = RpcLimiters::new().with_metrics(metrics.clone());
+        let limiter = limiters.endpoint("https://mainnet.example.io/v3/SECRET", &limit(2, None, Some(4)));
+        assert_eq!(limiter.try_acquire(CallerClass::User), Ok(()));
+
+        let exported = metrics.export_metrics().unwrap();
+        assert!(
+            exported.contains(r#"rpc_bucket_utilization{endpoint="mainnet.example.io",window="second"} 0.5"#),
+            "{}",
+            exported
+        );
+        assert!(exported.contains(r#"window="day"} 0.25"#), "{}", exported);
+        assert!(!exported.contains("SECRET"));
+
+        // same URL shares the limiter; same host with another key gets its own label
+        let again = limiters.endpoint("https://mainnet.example.io/v3/SECRET", &limit(50, None, None));
+        assert!(Arc::ptr_eq(&limiter, &again));
+        let other = limiters.endpoint("https://mainnet.example.io/v3/OTHER", &limit(2, None, None));
+        assert_eq!(other.label(), "mainnet.example.io#2");
+        assert_eq!(limiters.utilization().len(), 2);
+    }
+
+    #[test]
+    fn test_caller_class_defaults_to_user() {
+        assert_eq!(CallerClass::current(), CallerClass::User);
+    }
+}
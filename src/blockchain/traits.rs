@@ -2,7 +2,9 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    blockchain::bridge::BridgeTransactionStatus, core::errors::WalletError,
+    blockchain::bridge::{BridgeDescription, BridgeTransactionStatus, NormalizedTransfer},
+    blockchain::erc20::{Erc20Error, TokenBalance},
+    core::errors::WalletError,
     core::wallet_info::SecureWalletData,
 };
 
@@ -18,6 +20,25 @@ pub trait Bridge: Send + Sync {
         amount: &str,
         wallet_data: &SecureWalletData,
     ) -> anyhow::Result<String>;
+    /// Chain pairs, tokens and limits this bridge serves.
+    async fn describe(&self) -> anyhow::Result<BridgeDescription>;
+    /// Starts a transfer whose token and amount were resolved on both legs.
+    /// The default hands the asset symbol and whole-token amount to
+    /// [`transfer_across_chains`](Self::transfer_across_chains).
+    async fn transfer_normalized(
+        &self,
+        transfer: &NormalizedTransfer,
+        wallet_data: &SecureWalletData,
+    ) -> anyhow::Result<String> {
+        self.transfer_across_chains(
+            &transfer.source.network,
+            &transfer.destination.network,
+            transfer.asset(),
+            &transfer.amount(),
+            wallet_data,
+        )
+        .await
+    }
 }
 
 /// Represents the status of a standard blockchain transaction.
@@ -74,6 +95,57 @@ pub trait BlockchainClient: Send + Sync {
     /// Gets the current block number.
     async fn get_block_number(&self) -> Result<u64, WalletError>;
 
+    /// Fetches a transaction with its receipt (if mined) for inspection.
+    /// `Ok(None)` when the node does not know the hash. Non-EVM clients do
+    /// not support this.
+    async fn get_transaction_details(
+        &self,
+        tx_hash: &str,
+    ) -> Result<Option<TransactionDetails>, WalletError> {
+        let _ = tx_hash;
+        Err(WalletError::NetworkError(format!(
+            "Transaction lookup is not supported on {}",
+            self.get_network_name()
+        )))
+    }
+
+    /// Balance of `address` in the ERC-20 token at `token_address`, read
+    /// with `balanceOf` and `decimals` calls. Only EVM clients support this.
+    async fn get_erc20_balance(&self, address: &str, token_address: &str) -> Result<TokenBalance, Erc20Error> {
+        let _ = (address, token_address);
+        let network = self.get_network_name();
+        Err(WalletError::NetworkError(format!("Token balances are not supported on {}", network)).into())
+    }
+
+    /// How much of `owner`'s balance in the ERC-20 token at `token_address`
+    /// `spender` may move, read with an `allowance` call. Only EVM clients
+    /// support this.
+    async fn get_erc20_allowance(
+        &self,
+        owner: &str,
+        spender: &str,
+        token_address: &str,
+    ) -> Result<ethers::types::U256, Erc20Error> {
+        let _ = (owner, spender, token_address);
+        let network = self.get_network_name();
+        Err(WalletError::NetworkError(format!("Token allowances are not supported on {}", network)).into())
+    }
+
+    /// Gas, gas price and nonce a plain transfer of `amount` (whole native
+    /// units) to `to` would be sent with now; nothing is signed or reserved.
+    /// `pk_or_address` is the sender's address or its hex private key. Only
+    /// EVM clients support this.
+    async fn estimate_transfer(
+        &self,
+        pk_or_address: &str,
+        to: &str,
+        amount: &str,
+    ) -> Result<TransferEstimate, EstimateError> {
+        let _ = (pk_or_address, to, amount);
+        let network = self.get_network_name();
+        Err(WalletError::NetworkError(format!("Transfer estimates are not supported on {}", network)).into())
+    }
+
     /// Validates if a given address string is valid for the blockchain.
     fn validate_address(&self, address: &str) -> anyhow::Result<bool>;
 
@@ -84,6 +156,68 @@ pub trait BlockchainClient: Send + Sync {
     fn get_native_token(&self) -> &str;
 }
 
+/// An EVM transaction as the node reports it, for read-only inspection.
+#[derive(Debug, Clone)]
+pub struct TransactionDetails {
+    pub transaction: ethers::types::Transaction,
+    /// `None` while pending
+    pub receipt: Option<ethers::types::TransactionReceipt>,
+    /// Chain head at lookup time
+    pub latest_block: u64,
+}
+
+impl TransactionDetails {
+    /// Block the transaction was mined in; `None` while pending
+    pub fn block_number(&self) -> Option<u64> {
+        self.receipt
+            .as_ref()
+            .and_then(|r| r.block_number)
+            .or(self.transaction.block_number)
+            .map(|b| b.as_u64())
+    }
+
+    /// Blocks on top of (and including) the inclusion block; 0 while pending
+    pub fn confirmations(&self) -> u64 {
+        self.block_number().map_or(0, |b| self.latest_block.saturating_sub(b) + 1)
+    }
+}
+
+/// What [`BlockchainClient::estimate_transfer`] found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferEstimate {
+    pub from: ethers::types::Address,
+    /// `eth_estimateGas` for the transfer
+    pub gas: ethers::types::U256,
+    pub gas_price: ethers::types::U256,
+    /// Next nonce of `from`, counting its pending transactions
+    pub nonce: u64,
+}
+
+impl TransferEstimate {
+    /// Fee in wei at the estimated gas and current gas price
+    pub fn total_fee(&self) -> ethers::types::U256 {
+        self.gas.saturating_mul(self.gas_price)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EstimateError {
+    /// The node simulated the transfer and it reverted
+    #[error("Transfer would revert: {0}")]
+    Reverted(String),
+    #[error(transparent)]
+    Rpc(#[from] WalletError),
+}
+
+impl EstimateError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            EstimateError::Reverted(_) => "GAS_ESTIMATION_REVERTED",
+            EstimateError::Rpc(_) => "GAS_ESTIMATION_FAILED",
+        }
+    }
+}
+
 /// Basic information about a transaction.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionInfo {
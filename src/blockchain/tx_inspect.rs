@@ -0,0 +1,236 @@
+//! Best-effort decoding of EVM calldata and token event logs.
+//!
+//! Only the calls this wallet itself builds or relays are recognised (ERC-20 /
+//! ERC-721 transfers and approvals, and the EIP-2771 forwarder `execute`).
+//! Anything else is reported by selector; decoding never fails a lookup.
+
+use ethers::abi::{self, ParamType, Token};
+use ethers::types::{Address, Log, H256, U256};
+use ethers::utils::{keccak256, to_checksum};
+use serde::Serialize;
+
+use crate::core::abi::selector_from_signature;
+use crate::relay::forwarder::EXECUTE_SIGNATURE;
+
+/// One decoded argument
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedParam {
+    pub name: &'static str,
+    /// Checksummed address, decimal integer or `0x` hex bytes
+    pub value: String,
+}
+
+/// What the calldata of a transaction says
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DecodedCall {
+    Decoded { method: &'static str, signature: &'static str, params: Vec<DecodedParam> },
+    Unknown { selector: String, calldata_len: usize },
+}
+
+/// ERC-20 / ERC-721 `Transfer` or `Approval` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedLog {
+    pub event: &'static str,
+    pub contract: String,
+    pub params: Vec<DecodedParam>,
+}
+
+struct KnownCall {
+    method: &'static str,
+    signature: &'static str,
+    names: &'static [&'static str],
+    types: fn() -> Vec<ParamType>,
+}
+
+const KNOWN_CALLS: &[KnownCall] = &[
+    KnownCall {
+        method: "transfer",
+        signature: "transfer(address,uint256)",
+        names: &["to", "amount"],
+        types: || vec![ParamType::Address, ParamType::Uint(256)],
+    },
+    KnownCall {
+        method: "transferFrom",
+        signature: "transferFrom(address,address,uint256)",
+        names: &["from", "to", "amount"],
+        types: || vec![ParamType::Address, ParamType::Address, ParamType::Uint(256)],
+    },
+    KnownCall {
+        method: "approve",
+        signature: "approve(address,uint256)",
+        names: &["spender", "amount"],
+        types: || vec![ParamType::Address, ParamType::Uint(256)],
+    },
+    KnownCall {
+        method: "safeTransferFrom",
+        signature: "safeTransferFrom(address,address,uint256)",
+        names: &["from", "to", "token_id"],
+        types: || vec![ParamType::Address, ParamType::Address, ParamType::Uint(256)],
+    },
+    KnownCall {
+        method: "safeTransferFrom",
+        signature: "safeTransferFrom(address,address,uint256,bytes)",
+        names: &["from", "to", "token_id", "data"],
+        types: || vec![ParamType::Address, ParamType::Address, ParamType::Uint(256), ParamType::Bytes],
+    },
+    KnownCall {
+        method: "execute",
+        signature: EXECUTE_SIGNATURE,
+        names: &["from", "to", "value", "gas", "nonce", "data", "valid_until", "signature"],
+        types: || {
+            vec![
+                ParamType::Tuple(vec![
+                    ParamType::Address,
+                    ParamType::Address,
+                    ParamType::Uint(256),
+                    ParamType::Uint(256),
+                    ParamType::Uint(256),
+                    ParamType::Bytes,
+                    ParamType::Uint(256),
+                ]),
+                ParamType::Bytes,
+            ]
+        },
+    },
+];
+
+fn render(token: &Token) -> String {
+    match token {
+        Token::Address(address) => to_checksum(address, None),
+        Token::Uint(value) | Token::Int(value) => value.to_string(),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => format!("0x{}", hex::encode(bytes)),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => value.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Tuples are flattened so `execute` reads like the forward request it carries.
+fn flatten(tokens: Vec<Token>) -> Vec<Token> {
+    tokens
+        .into_iter()
+        .flat_map(|token| match token {
+            Token::Tuple(inner) => flatten(inner),
+            token => vec![token],
+        })
+        .collect()
+}
+
+/// `None` for empty calldata (a plain value transfer).
+pub fn decode_call(data: &[u8]) -> Option<DecodedCall> {
+    if data.is_empty() {
+        return None;
+    }
+    let unknown = || DecodedCall::Unknown {
+        selector: format!("0x{}", hex::encode(&data[..data.len().min(4)])),
+        calldata_len: data.len(),
+    };
+    if data.len() < 4 {
+        return Some(unknown());
+    }
+    let (selector, args) = data.split_at(4);
+    let decoded = KNOWN_CALLS
+        .iter()
+        .filter(|call| selector_from_signature(call.signature) == selector)
+        .find_map(|call| {
+            let tokens = flatten(abi::decode(&(call.types)(), args).ok()?);
+            let params = call
+                .names
+                .iter()
+                .zip(&tokens)
+                .map(|(name, token)| DecodedParam { name, value: render(token) })
+                .collect();
+            Some(DecodedCall::Decoded { method: call.method, signature: call.signature, params })
+        });
+    Some(decoded.unwrap_or_else(unknown))
+}
+
+fn topic_address(topic: &H256) -> String {
+    to_checksum(&Address::from(*topic), None)
+}
+
+/// Decodes `Transfer` / `Approval`; ERC-721 variants carry the token id as a
+/// fourth topic instead of data.
+pub fn decode_log(log: &Log) -> Option<DecodedLog> {
+    let (event, names) = if log.topics.first()? == &H256::from(keccak256("Transfer(address,address,uint256)")) {
+        ("Transfer", ["from", "to"])
+    } else if log.topics[0] == H256::from(keccak256("Approval(address,address,uint256)")) {
+        ("Approval", ["owner", "spender"])
+    } else {
+        return None;
+    };
+    let mut params: Vec<DecodedParam> = names
+        .iter()
+        .zip(log.topics.get(1..3)?)
+        .map(|(name, topic)| DecodedParam { name, value: topic_address(topic) })
+        .collect();
+    let last = match log.topics.get(3) {
+        Some(token_id) => DecodedParam { name: "token_id", value: U256::from_big_endian(token_id.as_bytes()).to_string() },
+        None if log.data.len() == 32 => DecodedParam { name: "value", value: U256::from_big_endian(&log.data).to_string() },
+        None => return None,
+    };
+    params.push(last);
+    Some(DecodedLog { event, contract: to_checksum(&log.address, None), params })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::abi::{abi_pack, abi_word_address, abi_word_uint256_from_str};
+    use ethers::types::Bytes;
+
+    const TO: &str = "0x000000000000000000000000000000000000dEaD";
+
+    #[test]
+    fn test_decode_transfer() {
+        let data = abi_pack(
+            selector_from_signature("transfer(address,uint256)"),
+            &[abi_word_address(TO).unwrap(), abi_word_uint256_from_str("2500000").unwrap()],
+        );
+        assert_eq!(
+            decode_call(&data),
+            Some(DecodedCall::Decoded {
+                method: "transfer",
+                signature: "transfer(address,uint256)",
+                params: vec![
+                    DecodedParam { name: "to", value: TO.to_string() },
+                    DecodedParam { name: "amount", value: "2500000".to_string() },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_and_truncated_calldata() {
+        assert_eq!(decode_call(&[]), None);
+        assert_eq!(
+            decode_call(&[0xde, 0xad, 0xbe, 0xef, 1, 2]),
+            Some(DecodedCall::Unknown { selector: "0xdeadbeef".to_string(), calldata_len: 6 })
+        );
+        // known selector but arguments missing
+        let selector = selector_from_signature("approve(address,uint256)");
+        assert!(matches!(decode_call(&selector), Some(DecodedCall::Unknown { calldata_len: 4, .. })));
+        assert!(matches!(decode_call(&[0xab]), Some(DecodedCall::Unknown { calldata_len: 1, .. })));
+    }
+
+    #[test]
+    fn test_decode_erc721_transfer_log() {
+        let from = "0x1111111111111111111111111111111111111111";
+        let log = Log {
+            address: "0x2222222222222222222222222222222222222222".parse().unwrap(),
+            topics: vec![
+                H256::from(keccak256("Transfer(address,address,uint256)")),
+                H256::from(abi_word_address(from).unwrap()),
+                H256::from(abi_word_address(TO).unwrap()),
+                H256::from_low_u64_be(7),
+            ],
+            data: Bytes::default(),
+            ..Default::default()
+        };
+        let decoded = decode_log(&log).unwrap();
+        assert_eq!(decoded.event, "Transfer");
+        assert_eq!(decoded.params[1].value, TO);
+        assert_eq!(decoded.params[2], DecodedParam { name: "token_id", value: "7".to_string() });
+    }
+}
@@ -0,0 +1,303 @@
+//! Transaction status pushes behind the `/api/ws` subscriptions.
+//!
+//! Sockets register `(wallet, network, hash)` subscriptions here and all
+//! listen on one `broadcast` channel. A single poller task asks the client of
+//! each subscribed transaction for [`BlockchainClient::get_transaction_status`]
+//! and publishes a [`TxStatusEvent`] whenever the answer changes. While the
+//! answer stays the same the gap to the next lookup doubles, up to the hub's
+//! maximum. A terminal status (`confirmed`, `failed`) is published once and
+//! ends the subscription. The poller exits when nothing is subscribed; the
+//! next subscription starts it again.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::{broadcast, Notify};
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::blockchain::traits::{BlockchainClient, TransactionStatus};
+use crate::ops::incidents::spawn_named;
+
+/// Gap between the first lookups of a transaction, and after every change
+pub const DEFAULT_STATUS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Longest gap an unchanged transaction backs off to
+pub const DEFAULT_STATUS_MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Events a slow socket may fall behind by before it misses some
+const EVENT_CAPACITY: usize = 256;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_CONFIRMED: &str = "confirmed";
+pub const STATUS_FAILED: &str = "failed";
+pub const STATUS_UNKNOWN: &str = "unknown";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TxStatusEvent {
+    pub wallet: String,
+    pub network: String,
+    pub tx_hash: String,
+    /// `pending` | `confirmed` | `failed` | `unknown` (the node does not know the hash)
+    pub status: &'static str,
+    /// Status published before this one; `None` for the first
+    pub previous: Option<&'static str>,
+}
+
+impl TxStatusEvent {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self.status, STATUS_CONFIRMED | STATUS_FAILED)
+    }
+
+    /// `(wallet, network, hash)` the event belongs to
+    pub fn key(&self) -> SubscriptionKey {
+        (self.wallet.clone(), self.network.clone(), self.tx_hash.clone())
+    }
+}
+
+fn status_name(status: &TransactionStatus) -> &'static str {
+    match status {
+        TransactionStatus::Pending => STATUS_PENDING,
+        TransactionStatus::Confirmed => STATUS_CONFIRMED,
+        TransactionStatus::Failed => STATUS_FAILED,
+        TransactionStatus::Unknown => STATUS_UNKNOWN,
+    }
+}
+
+/// `(wallet, network, lowercase hash)`
+pub type SubscriptionKey = (String, String, String);
+
+/// Returned by [`TxStatusHub::subscribe`]; pass it back to unsubscribe.
+///
+/// The generation tells a subscription apart from a later one on the same
+/// key, so a socket that closes late never drops somebody else's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionHandle {
+    pub key: SubscriptionKey,
+    generation: u64,
+}
+
+struct Subscription {
+    client: Arc<dyn BlockchainClient>,
+    generation: u64,
+    subscribers: usize,
+    last: Option<&'static str>,
+    next_poll: Instant,
+    backoff: Duration,
+}
+
+#[derive(Default)]
+struct HubState {
+    subscriptions: HashMap<SubscriptionKey, Subscription>,
+    next_generation: u64,
+    polling: bool,
+}
+
+pub struct TxStatusHub {
+    poll_interval: Duration,
+    max_backoff: Duration,
+    events: broadcast::Sender<TxStatusEvent>,
+    state: Arc<Mutex<HubState>>,
+    wake: Arc<Notify>,
+}
+
+impl Default for TxStatusHub {
+    fn default() -> Self {
+        Self::new(DEFAULT_STATUS_POLL_INTERVAL, DEFAULT_STATUS_MAX_BACKOFF)
+    }
+}
+
+impl TxStatusHub {
+    pub fn new(poll_interval: Duration, max_backoff: Duration) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            poll_interval,
+            max_backoff: max_backoff.max(poll_interval),
+            events,
+            state: Arc::default(),
+            wake: Arc::default(),
+        }
+    }
+
+    /// Every event the hub publishes from now on. Take the receiver before
+    /// subscribing so no change can slip in between.
+    pub fn events(&self) -> broadcast::Receiver<TxStatusEvent> {
+        self.events.subscribe()
+    }
+
+    /// Subscriptions currently polled
+    pub fn active(&self) -> usize {
+        self.state.lock().subscriptions.len()
+    }
+
+    /// Joins the subscription on `(wallet, network, hash)`, starting it if
+    /// nobody follows the transaction yet. Also returns the last status
+    /// published for it, if any, so a late subscriber is not left waiting
+    /// for the next change.
+    pub fn subscribe(
+        &self,
+        wallet: &str,
+        network: &str,
+        tx_hash: &str,
+        client: Arc<dyn BlockchainClient>,
+    ) -> (SubscriptionHandle, Option<TxStatusEvent>) {
+        let key = (wallet.to_string(), network.to_string(), tx_hash.to_ascii_lowercase());
+        let mut state = self.state.lock();
+        let HubState { subscriptions, next_generation, polling } = &mut *state;
+        let subscription = subscriptions.entry(key.clone()).or_insert_with(|| {
+            *next_generation += 1;
+            Subscription {
+                client,
+                generation: *next_generation,
+                subscribers: 0,
+                last: None,
+                next_poll: Instant::now(),
+                backoff: self.poll_interval,
+            }
+        });
+        subscription.subscribers += 1;
+        let current = subscription.last.map(|status| TxStatusEvent {
+            wallet: key.0.clone(),
+            network: key.1.clone(),
+            tx_hash: key.2.clone(),
+            status,
+            previous: None,
+        });
+        let handle = SubscriptionHandle { key, generation: subscription.generation };
+
+        if *polling {
+            self.wake.notify_one();
+        } else {
+            *polling = true;
+            let poller = Poller {
+                state: self.state.clone(),
+                events: self.events.clone(),
+                wake: self.wake.clone(),
+                poll_interval: self.poll_interval,
+                max_backoff: self.max_backoff,
+            };
+            spawn_named("tx_status_hub", poller.run());
+        }
+        (handle, current)
+    }
+
+    /// Leaves a subscription; the last subscriber to leave ends it. A
+    /// subscription that already ended (terminal status) is left alone.
+    pub fn unsubscribe(&self, handle: &SubscriptionHandle) {
+        let mut state = self.state.lock();
+        let Some(subscription) = state.subscriptions.get_mut(&handle.key) else {
+            return;
+        };
+        if subscription.generation != handle.generation {
+            return;
+        }
+        subscription.subscribers = subscription.subscribers.saturating_sub(1);
+        if subscription.subscribers == 0 {
+            state.subscriptions.remove(&handle.key);
+        }
+    }
+}
+
+struct Poller {
+    state: Arc<Mutex<HubState>>,
+    events: broadcast::Sender<TxStatusEvent>,
+    wake: Arc<Notify>,
+    poll_interval: Duration,
+    max_backoff: Duration,
+}
+
+impl Poller {
+    async fn run(self) {
+        loop {
+            let (due, next_poll) = {
+                let mut state = self.state.lock();
+                // `subscribe` checks `polling` under the same lock, so a new
+                // subscription either sees this poller running or starts another
+                if state.subscriptions.is_empty() {
+                    state.polling = false;
+                    return;
+                }
+                let now = Instant::now();
+                let due: Vec<(SubscriptionKey, u64, Arc<dyn BlockchainClient>)> = state
+                    .subscriptions
+                    .iter()
+                    .filter(|(_, s)| s.next_poll <= now)
+                    .map(|(key, s)| (key.clone(), s.generation, s.client.clone()))
+                    .collect();
+                let next_poll = state.subscriptions.values().map(|s| s.next_poll).min().unwrap_or(now);
+                (due, next_poll)
+            };
+            if due.is_empty() {
+                tokio::select! {
+                    _ = tokio::time::sleep_until(next_poll) => {}
+                    _ = self.wake.notified() => {}
+                }
+                continue;
+            }
+            for (key, generation, client) in due {
+                let status = match client.get_transaction_status(&key.2).await {
+                    Ok(status) => Some(status_name(&status)),
+                    Err(e) => {
+                        // a failed lookup keeps the last known status and backs off
+                        debug!("tx status {} on {}: {}", key.2, key.1, e);
+                        None
+                    }
+                };
+                self.record(&key, generation, status);
+            }
+        }
+    }
+
+    fn record(&self, key: &SubscriptionKey, generation: u64, status: Option<&'static str>) {
+        let mut state = self.state.lock();
+        let Some(subscription) = state.subscriptions.get_mut(key) else {
+            return;
+        };
+        // everyone left, and maybe somebody subscribed again, during the lookup
+        if subscription.generation != generation {
+            return;
+        }
+        let Some(status) = status.filter(|s| subscription.last != Some(*s)) else {
+            subscription.next_poll = Instant::now() + subscription.backoff;
+            subscription.backoff = (subscription.backoff * 2).min(self.max_backoff);
+            return;
+        };
+        let event = TxStatusEvent {
+            wallet: key.0.clone(),
+            network: key.1.clone(),
+            tx_hash: key.2.clone(),
+            status,
+            previous: subscription.last,
+        };
+        subscription.last = Some(status);
+        subscription.backoff = self.poll_interval;
+        subscription.next_poll = Instant::now() + self.poll_interval;
+        if event.is_terminal() {
+            state.subscriptions.remove(key);
+        }
+        // no socket listening right now is fine
+        let _ = self.events.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_statuses() {
+        let event = |status| TxStatusEvent {
+            wallet: "alice".to_string(),
+            network: "eth".to_string(),
+            tx_hash: "0x01".to_string(),
+            status,
+            previous: None,
+        };
+        assert!(!event(STATUS_PENDING).is_terminal());
+        assert!(!event(STATUS_UNKNOWN).is_terminal());
+        assert!(event(STATUS_CONFIRMED).is_terminal());
+        assert!(event(STATUS_FAILED).is_terminal());
+        assert_eq!(status_name(&TransactionStatus::Failed), STATUS_FAILED);
+    }
+}
@@ -0,0 +1,295 @@
+//! Shared confirmation watches for `GET /api/transactions/:id/wait`.
+//!
+//! Every waiter on the same `(network, hash)` subscribes to one poller task
+//! that publishes what the node reports into a `watch` channel. The poller
+//! stops once the last waiter has gone, so the number of RPC calls does not
+//! grow with the number of clients waiting.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::types::U64;
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::blockchain::traits::{BlockchainClient, TransactionDetails};
+use crate::ops::incidents::spawn_named;
+
+/// Default gap between two lookups of the same transaction
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub const WAIT_PENDING: &str = "pending";
+pub const WAIT_CONFIRMING: &str = "confirming";
+pub const WAIT_CONFIRMED: &str = "confirmed";
+pub const WAIT_FAILED: &str = "failed";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReceiptSummary {
+    pub block_number: Option<u64>,
+    /// `success` | `failed`
+    pub status: &'static str,
+    pub gas_used: Option<String>,
+    /// wei
+    pub effective_gas_price: Option<String>,
+}
+
+/// What the last lookup returned; shared by all waiters of one watch
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Observation {
+    pub polls: u64,
+    /// The node knows the transaction (pending or mined)
+    pub seen: bool,
+    pub block_number: Option<u64>,
+    pub confirmations: u64,
+    pub receipt: Option<ReceiptSummary>,
+}
+
+impl Observation {
+    fn from_details(polls: u64, details: &TransactionDetails) -> Self {
+        let receipt = details.receipt.as_ref().map(|r| ReceiptSummary {
+            block_number: r.block_number.map(|b| b.as_u64()),
+            status: match r.status {
+                Some(s) if s != U64::from(1) => "failed",
+                _ => "success",
+            },
+            gas_used: r.gas_used.map(|g| g.to_string()),
+            effective_gas_price: r.effective_gas_price.map(|p| p.to_string()),
+        });
+        Self {
+            polls,
+            seen: true,
+            block_number: details.block_number(),
+            confirmations: details.confirmations(),
+            receipt,
+        }
+    }
+}
+
+/// One waiter's view of the watch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TxWaitStatus {
+    pub tx_hash: String,
+    pub network: String,
+    /// `pending` | `confirming` | `confirmed` | `failed`
+    pub status: &'static str,
+    pub confirmations: u64,
+    pub required_confirmations: u64,
+    pub block_number: Option<u64>,
+    pub receipt: Option<ReceiptSummary>,
+    pub elapsed_ms: u64,
+    pub timed_out: bool,
+}
+
+impl TxWaitStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self.status, WAIT_CONFIRMED | WAIT_FAILED)
+    }
+}
+
+type WatchKey = (String, String);
+
+pub struct TxWatchRegistry {
+    poll_interval: Duration,
+    watches: Arc<Mutex<HashMap<WatchKey, watch::Sender<Observation>>>>,
+    pollers_started: AtomicUsize,
+}
+
+impl Default for TxWatchRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_POLL_INTERVAL)
+    }
+}
+
+impl TxWatchRegistry {
+    pub fn new(poll_interval: Duration) -> Self {
+        Self { poll_interval, watches: Arc::default(), pollers_started: AtomicUsize::new(0) }
+    }
+
+    /// Poller tasks started so far
+    pub fn pollers_started(&self) -> usize {
+        self.pollers_started.load(Ordering::Relaxed)
+    }
+
+    /// Watches currently polling
+    pub fn active(&self) -> usize {
+        self.watches.lock().len()
+    }
+
+    /// Joins the watch on `(network, hash)`, starting its poller if nobody is
+    /// waiting on it yet. The poller keeps running while any `TxWaiter` of
+    /// the watch is alive.
+    pub fn wait(
+        &self,
+        network: &str,
+        tx_hash: &str,
+        required_confirmations: u64,
+        deadline: Instant,
+        client: Arc<dyn BlockchainClient>,
+    ) -> TxWaiter {
+        let key = (network.to_string(), tx_hash.to_ascii_lowercase());
+        let rx = {
+            let mut watches = self.watches.lock();
+            match watches.get(&key) {
+                Some(tx) => tx.subscribe(),
+                None => {
+                    let (tx, rx) = watch::channel(Observation::default());
+                    watches.insert(key.clone(), tx.clone());
+                    self.pollers_started.fetch_add(1, Ordering::Relaxed);
+                    spawn_named("tx_watch", poll(self.watches.clone(), key, tx, client, self.poll_interval));
+                    rx
+                }
+            }
+        };
+        TxWaiter {
+            rx,
+            tx_hash: tx_hash.to_string(),
+            network: network.to_string(),
+            required_confirmations: required_confirmations.max(1),
+            started: Instant::now(),
+            deadline,
+            last_reported: None,
+        }
+    }
+}
+
+async fn poll(
+    watches: Arc<Mutex<HashMap<WatchKey, watch::Sender<Observation>>>>,
+    key: WatchKey,
+    tx: watch::Sender<Observation>,
+    client: Arc<dyn BlockchainClient>,
+    interval: Duration,
+) {
+    let mut polls = 0;
+    loop {
+        // subscribe() happens under the same lock, so nobody can join a watch
+        // whose poller has already decided to stop
+        {
+            let mut watches = watches.lock();
+            if tx.receiver_count() == 0 {
+                watches.remove(&key);
+                return;
+            }
+        }
+        polls += 1;
+        match client.get_transaction_details(&key.1).await {
+            Ok(Some(details)) => {
+                tx.send_replace(Observation::from_details(polls, &details));
+            }
+            Ok(None) => {
+                tx.send_replace(Observation { polls, ..Observation::default() });
+            }
+            // a failed lookup keeps the last known state
+            Err(e) => debug!("tx watch {} on {}: {}", key.1, key.0, e),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+pub struct TxWaiter {
+    rx: watch::Receiver<Observation>,
+    tx_hash: String,
+    network: String,
+    required_confirmations: u64,
+    started: Instant,
+    deadline: Instant,
+    last_reported: Option<(&'static str, u64)>,
+}
+
+impl TxWaiter {
+    /// Current state; `timed_out` is set once the deadline has passed
+    pub fn latest(&self) -> TxWaitStatus {
+        let observation = self.rx.borrow().clone();
+        let status = match &observation.receipt {
+            Some(r) if r.status == "failed" => WAIT_FAILED,
+            _ if observation.block_number.is_none() => WAIT_PENDING,
+            _ if observation.confirmations >= self.required_confirmations => WAIT_CONFIRMED,
+            _ => WAIT_CONFIRMING,
+        };
+        TxWaitStatus {
+            tx_hash: self.tx_hash.clone(),
+            network: self.network.clone(),
+            status,
+            confirmations: observation.confirmations,
+            required_confirmations: self.required_confirmations,
+            block_number: observation.block_number,
+            receipt: observation.receipt,
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            timed_out: Instant::now() >= self.deadline,
+        }
+    }
+
+    /// Next state that differs in status or confirmation count from the
+    /// previous one returned; `None` once the deadline has passed.
+    pub async fn next_update(&mut self) -> Option<TxWaitStatus> {
+        loop {
+            let polled = self.rx.borrow_and_update().polls > 0;
+            if polled {
+                let current = self.latest();
+                let reported = (current.status, current.confirmations);
+                if self.last_reported != Some(reported) {
+                    self.last_reported = Some(reported);
+                    return Some(current);
+                }
+            }
+            match tokio::time::timeout_at(self.deadline, self.rx.changed()).await {
+                Ok(Ok(())) => {}
+                // the poller only stops once no waiter is left
+                Ok(Err(_)) | Err(_) => return None,
+            }
+        }
+    }
+
+    /// Waits for a terminal state; the latest (timed out) state otherwise
+    pub async fn until_terminal(&mut self) -> TxWaitStatus {
+        while let Some(status) = self.next_update().await {
+            if status.is_terminal() {
+                return status;
+            }
+        }
+        self.latest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waiter_status_uses_own_target() {
+        let (tx, rx) = watch::channel(Observation::default());
+        let waiter = |required| TxWaiter {
+            rx: rx.clone(),
+            tx_hash: "0x01".to_string(),
+            network: "eth".to_string(),
+            required_confirmations: required,
+            started: Instant::now(),
+            deadline: Instant::now() + Duration::from_secs(60),
+            last_reported: None,
+        };
+        assert_eq!(waiter(3).latest().status, WAIT_PENDING);
+
+        let mined = ReceiptSummary {
+            block_number: Some(100),
+            status: "success",
+            gas_used: None,
+            effective_gas_price: None,
+        };
+        tx.send_replace(Observation {
+            polls: 2,
+            seen: true,
+            block_number: Some(100),
+            confirmations: 2,
+            receipt: Some(mined.clone()),
+        });
+        assert_eq!(waiter(3).latest().status, WAIT_CONFIRMING);
+        assert_eq!(waiter(2).latest().status, WAIT_CONFIRMED);
+
+        tx.send_modify(|o| o.receipt = Some(ReceiptSummary { status: "failed", ..mined }));
+        assert!(waiter(12).latest().is_terminal());
+    }
+}
@@ -0,0 +1,192 @@
+//! What code is running, and with which configuration.
+//!
+//! [`BuildInfo`] is fixed at compile time by `build.rs`: the git commit and
+//! whether the tree had uncommitted changes, the compiler, the build time and
+//! the cargo features. The timestamp is `SOURCE_DATE_EPOCH` or the commit
+//! time, so rebuilding a commit reproduces it.
+//!
+//! [`RuntimeInfo`] describes this process: a checksum of the effective
+//! configuration, the database schema version, which optional subsystems are
+//! on, and when the process started. The checksum is taken over the parsed
+//! configuration rather than the file, with object keys sorted, so reformatting
+//! `config.toml` or reordering its keys leaves it unchanged while any changed
+//! value (including one set through an environment variable) moves it.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::core::config::WalletConfig;
+use crate::util::canonical_json;
+
+/// Compile-time facts about this binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    pub version: String,
+    pub git_commit: String,
+    /// Tracked files differed from the commit when this was built.
+    pub git_dirty: bool,
+    pub rustc_version: String,
+    /// RFC 3339
+    pub build_timestamp: String,
+    pub profile: String,
+    /// Enabled cargo features, sorted.
+    pub features: Vec<String>,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        let built = env!("BUILD_TIMESTAMP").parse::<i64>().ok().and_then(|secs| Utc.timestamp_opt(secs, 0).single());
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: env!("BUILD_GIT_COMMIT").to_string(),
+            git_dirty: env!("BUILD_GIT_DIRTY") == "true",
+            rustc_version: env!("BUILD_RUSTC_VERSION").to_string(),
+            build_timestamp: built.map(|t| t.to_rfc3339()).unwrap_or_else(|| "unknown".to_string()),
+            profile: if cfg!(debug_assertions) { "debug" } else { "release" }.to_string(),
+            features: enabled_features().into_iter().map(str::to_string).collect(),
+        }
+    }
+
+    /// Abbreviated commit with a `-dirty` suffix, as shown in logs.
+    pub fn describe(&self) -> String {
+        let commit = self.git_commit.get(..12).unwrap_or(&self.git_commit);
+        if self.git_dirty {
+            format!("{}-dirty", commit)
+        } else {
+            commit.to_string()
+        }
+    }
+}
+
+/// Cargo features this crate was compiled with, as declared in `Cargo.toml`.
+pub fn enabled_features() -> Vec<&'static str> {
+    env!("BUILD_FEATURES").split(',').filter(|f| !f.is_empty()).collect()
+}
+
+/// Facts about the running process and its configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeInfo {
+    /// Hex SHA-256 of the effective configuration; see [`config_checksum`].
+    pub config_checksum: String,
+    /// Highest schema version recorded in the database; `None` if it could
+    /// not be read.
+    pub schema_version: Option<i64>,
+    /// Optional subsystems and whether each is on.
+    pub subsystems: BTreeMap<String, bool>,
+    pub started_at: DateTime<Utc>,
+    pub uptime_secs: u64,
+}
+
+impl RuntimeInfo {
+    pub fn collect(config: &WalletConfig, schema_version: Option<i64>) -> Self {
+        let subsystems = [
+            // Prometheus metrics are always registered
+            ("telemetry", true),
+            ("quantum_safe", config.quantum_safe),
+            ("hardware_trezor", cfg!(feature = "trezor")),
+            ("hardware_ledger", cfg!(feature = "ledger")),
+            ("ai_anomaly_detection", cfg!(feature = "ai-anomaly-detection")),
+            ("pricing", config.pricing.enabled),
+            ("relay", config.relay.enabled),
+            ("backups", config.backups.enabled),
+            ("fee_tracking", config.fee_tracking.enabled),
+            ("balance_snapshots", config.balance_snapshots.enabled),
+            ("deadman", config.security.deadman.enabled),
+        ];
+        Self {
+            config_checksum: config_checksum(config),
+            schema_version,
+            subsystems: subsystems.into_iter().map(|(name, on)| (name.to_string(), on)).collect(),
+            started_at: started_at(),
+            uptime_secs: uptime().as_secs(),
+        }
+    }
+}
+
+/// One line for the startup log.
+pub fn banner(build: &BuildInfo, runtime: &RuntimeInfo) -> String {
+    let enabled: Vec<&str> = runtime.subsystems.iter().filter(|(_, on)| **on).map(|(name, _)| name.as_str()).collect();
+    format!(
+        "defi-hot-wallet {} ({}, {}, built {}, {}, features [{}]); config {}, schema v{}, subsystems [{}]",
+        build.version,
+        build.describe(),
+        build.rustc_version,
+        build.build_timestamp,
+        build.profile,
+        build.features.join(", "),
+        runtime.config_checksum.get(..16).unwrap_or(&runtime.config_checksum),
+        runtime.schema_version.map(|v| v.to_string()).unwrap_or_else(|| "?".to_string()),
+        enabled.join(", "),
+    )
+}
+
+struct ProcessStart {
+    at: DateTime<Utc>,
+    instant: Instant,
+}
+
+static PROCESS_START: OnceLock<ProcessStart> = OnceLock::new();
+
+fn process_start() -> &'static ProcessStart {
+    PROCESS_START.get_or_init(|| ProcessStart { at: Utc::now(), instant: Instant::now() })
+}
+
+/// Pins the process start time; call first thing in `main`. Without it the
+/// first caller of [`started_at`] or [`uptime`] pins it.
+pub fn mark_process_start() {
+    process_start();
+}
+
+pub fn started_at() -> DateTime<Utc> {
+    process_start().at
+}
+
+/// Time since the process started, from the monotonic clock.
+pub fn uptime() -> Duration {
+    process_start().instant.elapsed()
+}
+
+/// Hex SHA-256 over the [canonical JSON](canonical_json) form of `config`.
+pub fn config_checksum<T: Serialize>(config: &T) -> String {
+    let canonical = match serde_json::to_value(config) {
+        Ok(value) => canonical_json::to_string(&value),
+        Err(e) => {
+            tracing::warn!("configuration could not be serialized for its checksum: {}", e);
+            "unserializable".to_string()
+        }
+    };
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_canonical_form_ignores_key_order() {
+        let a: Value = serde_json::from_str(r#"{"b": [1, {"y": 2, "x": "s"}], "a": null}"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{"a":null,"b":[1,{"x":"s","y":2}]}"#).unwrap();
+        assert_eq!(canonical_json::to_string(&a), r#"{"a":null,"b":[1,{"x":"s","y":2}]}"#);
+        assert_eq!(config_checksum(&a), config_checksum(&b));
+        // array order is meaningful
+        let c: Value = serde_json::from_str(r#"{"a":null,"b":[{"x":"s","y":2},1]}"#).unwrap();
+        assert_ne!(config_checksum(&a), config_checksum(&c));
+    }
+
+    #[test]
+    fn test_describe_marks_dirty_builds() {
+        let mut build = BuildInfo::current();
+        build.git_commit = "0123456789abcdef0123".to_string();
+        build.git_dirty = true;
+        assert_eq!(build.describe(), "0123456789ab-dirty");
+        build.git_dirty = false;
+        build.git_commit = "unknown".to_string();
+        assert_eq!(build.describe(), "unknown");
+    }
+}
@@ -0,0 +1,374 @@
+//! CLI 金额展示与输入解析
+//!
+//! 输出统一为「按 locale 分组的十进制值 + 代币符号 + 括号内的最小单位精确值」，
+//! 例如 `1,234.5 ETH (1234500000000000000000 wei)`；括号内的值不分组，可直接复制。
+//!
+//! 输入不随 locale 变化（`1,5` 在 de 与 en 下含义不同，一律拒绝）：
+//! - `1.5eth` / `1.5 ETH`：主单位，数字部分走 [`Amount`] 的语法
+//! - `1500000000000000000wei`：最小单位，只能是整数
+//! - 不带后缀的小数按主单位处理；不带后缀的整数有歧义，必须写明单位
+
+use std::fmt;
+
+use crate::api::validators::{Amount, ParamError};
+use crate::i18n::I18nManager;
+
+/// 链原生资产的展示单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Denomination {
+    pub symbol: &'static str,
+    pub minimal_unit: &'static str,
+    pub decimals: u32,
+}
+
+impl Denomination {
+    pub const ETH: Self = Self { symbol: "ETH", minimal_unit: "wei", decimals: 18 };
+    pub const MATIC: Self = Self { symbol: "MATIC", minimal_unit: "wei", decimals: 18 };
+    pub const BNB: Self = Self { symbol: "BNB", minimal_unit: "wei", decimals: 18 };
+    pub const BTC: Self = Self { symbol: "BTC", minimal_unit: "sat", decimals: 8 };
+
+    pub fn for_network(network: &str) -> Option<Self> {
+        match network {
+            "eth" | "ethereum" | "sepolia" => Some(Self::ETH),
+            "polygon" | "polygon-testnet" => Some(Self::MATIC),
+            "bsc" | "bsctestnet" | "binance" | "bnb" => Some(Self::BNB),
+            "btc" | "bitcoin" => Some(Self::BTC),
+            _ => None,
+        }
+    }
+
+    fn is_minimal_suffix(&self, suffix: &str) -> bool {
+        suffix == self.minimal_unit || (self.minimal_unit == "sat" && suffix == "sats")
+    }
+}
+
+/// 千分位与小数点分隔符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLocale {
+    pub group: &'static str,
+    pub decimal: &'static str,
+}
+
+impl NumberLocale {
+    pub const EN: Self = Self { group: ",", decimal: "." };
+    pub const DE: Self = Self { group: ".", decimal: "," };
+    /// fr / ru 等：窄不换行空格分组
+    pub const SPACE: Self = Self { group: "\u{202f}", decimal: "," };
+
+    /// 按语言主标签选择（`de-AT` → de）；未知语言按 en
+    pub fn for_language(language: &str) -> Self {
+        let primary = language.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+        match primary.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" => Self::DE,
+            "fr" | "ru" | "pl" | "cs" | "sv" | "fi" | "nb" | "uk" => Self::SPACE,
+            _ => Self::EN,
+        }
+    }
+
+    /// `--locale` 优先，其次用户偏好，最后 i18n 管理器的默认语言
+    pub fn resolve(flag: Option<&str>, preference: Option<&str>, i18n: &I18nManager) -> Self {
+        let language = flag
+            .or(preference)
+            .filter(|l| !l.trim().is_empty())
+            .unwrap_or_else(|| i18n.default_language());
+        Self::for_language(language)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AmountInputError {
+    #[error("Amount {0:?} is ambiguous: add a unit suffix such as `{1}` or `{2}`")]
+    Ambiguous(String, String, String),
+    #[error("Unknown unit {unit:?}; expected `{symbol}` or `{minimal}`")]
+    UnknownUnit { unit: String, symbol: String, minimal: String },
+    #[error("Invalid amount: {0}")]
+    Invalid(#[from] ParamError),
+    #[error("{unit} amounts must be whole numbers")]
+    FractionalMinimal { unit: String },
+    #[error("{symbol} supports at most {decimals} decimal places")]
+    TooPrecise { symbol: String, decimals: u32 },
+    #[error("Amount is too large")]
+    Overflow,
+}
+
+/// 解析 CLI 金额参数，返回最小单位数量
+pub fn parse_amount(input: &str, denom: &Denomination) -> Result<u128, AmountInputError> {
+    let input = input.trim();
+    let split = input.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(input.len());
+    let (number, suffix) = (input[..split].trim(), input[split..].trim().to_ascii_lowercase());
+
+    let minimal = if suffix.is_empty() {
+        if !number.contains('.') {
+            return Err(AmountInputError::Ambiguous(
+                input.to_string(),
+                format!("{}{}", number, denom.symbol.to_ascii_lowercase()),
+                format!("{}{}", number, denom.minimal_unit),
+            ));
+        }
+        false
+    } else if suffix == denom.symbol.to_ascii_lowercase() {
+        false
+    } else if denom.is_minimal_suffix(&suffix) {
+        true
+    } else {
+        return Err(AmountInputError::UnknownUnit {
+            unit: suffix,
+            symbol: denom.symbol.to_ascii_lowercase(),
+            minimal: denom.minimal_unit.to_string(),
+        });
+    };
+
+    if minimal {
+        if number.contains('.') {
+            return Err(AmountInputError::FractionalMinimal { unit: denom.minimal_unit.to_string() });
+        }
+        // 同一套语法check（无符号、无前导零、非零）
+        Amount::try_from(number)?;
+        return number.parse::<u128>().map_err(|_| AmountInputError::Overflow);
+    }
+
+    Amount::try_from(number)?;
+    from_decimal(number, denom)
+}
+
+/// 主单位十进制串 → 最小单位，如节点返回的balance `0.000000`
+///
+/// 只接受数字与一个小数点，允许为 0；用户输入请走 [`parse_amount`]。
+pub fn from_decimal(value: &str, denom: &Denomination) -> Result<u128, AmountInputError> {
+    let (int, frac) = value.split_once('.').unwrap_or((value, ""));
+    if int.is_empty() || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+        return Err(AmountInputError::Invalid(ParamError::AmountFormat));
+    }
+    let frac = frac.trim_end_matches('0');
+    if frac.len() > denom.decimals as usize {
+        return Err(AmountInputError::TooPrecise { symbol: denom.symbol.to_string(), decimals: denom.decimals });
+    }
+    let scale = 10u128.checked_pow(denom.decimals).ok_or(AmountInputError::Overflow)?;
+    let int: u128 = int.parse().map_err(|_| AmountInputError::Overflow)?;
+    let frac: u128 = if frac.is_empty() {
+        0
+    } else {
+        format!("{:0<width$}", frac, width = denom.decimals as usize)
+            .parse()
+            .map_err(|_| AmountInputError::Overflow)?
+    };
+    int.checked_mul(scale).and_then(|v| v.checked_add(frac)).ok_or(AmountInputError::Overflow)
+}
+
+/// 主单位十进制串（无分组，去掉末尾的 0），如 `1.5`
+pub fn to_decimal(minimal: u128, denom: &Denomination) -> String {
+    let scale = 10u128.pow(denom.decimals);
+    let (int, frac) = (minimal / scale, minimal % scale);
+    if frac == 0 {
+        return int.to_string();
+    }
+    let frac = format!("{:0>width$}", frac, width = denom.decimals as usize);
+    format!("{}.{}", int, frac.trim_end_matches('0'))
+}
+
+fn group_digits(digits: &str, separator: &str) -> String {
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 * separator.len());
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push_str(separator);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// 一个可展示的金额
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayAmount<'a> {
+    pub minimal: u128,
+    pub denom: &'a Denomination,
+    pub locale: &'a NumberLocale,
+}
+
+impl fmt::Display for DisplayAmount<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimal = to_decimal(self.minimal, self.denom);
+        let (int, frac) = decimal.split_once('.').unwrap_or((&decimal, ""));
+        write!(f, "{}", group_digits(int, self.locale.group))?;
+        if !frac.is_empty() {
+            write!(f, "{}{}", self.locale.decimal, frac)?;
+        }
+        write!(f, " {} ({} {})", self.denom.symbol, self.minimal, self.denom.minimal_unit)
+    }
+}
+
+pub fn format_amount(minimal: u128, denom: &Denomination, locale: &NumberLocale) -> String {
+    DisplayAmount { minimal, denom, locale }.to_string()
+}
+
+/// 发送确认提示所需信息
+#[derive(Debug, Clone)]
+pub struct SendPreview<'a> {
+    pub wallet: &'a str,
+    pub network: &'a str,
+    pub to: &'a str,
+    pub amount: u128,
+    pub fee: u128,
+}
+
+/// 发送确认提示：金额、手续费与合计都带两种表示
+pub fn send_prompt(preview: &SendPreview<'_>, denom: &Denomination, locale: &NumberLocale) -> String {
+    let show = |minimal| format_amount(minimal, denom, locale);
+    let total = preview.amount.saturating_add(preview.fee);
+    format!(
+        "Send from wallet '{}' on {}\n  To:     {}\n  Amount: {}\n  Fee:    {}\n  Total:  {}\nProceed? [y/N] ",
+        preview.wallet,
+        preview.network,
+        preview.to,
+        show(preview.amount),
+        show(preview.fee),
+        show(total),
+    )
+}
+
+/// 清空wallet的确认提示；转出金额 = balance - 手续费
+///
+/// balance不足以支付手续费时返回 `None`。
+pub fn sweep_prompt(
+    wallet: &str,
+    network: &str,
+    to: &str,
+    balance: u128,
+    fee: u128,
+    denom: &Denomination,
+    locale: &NumberLocale,
+) -> Option<String> {
+    let amount = balance.checked_sub(fee).filter(|amount| *amount > 0)?;
+    let show = |minimal| format_amount(minimal, denom, locale);
+    Some(format!(
+        "Sweep all funds from wallet '{}' on {}\n  To:      {}\n  Balance: {}\n  Fee:     {}\n  Sends:   {}\nProceed? [y/N] ",
+        wallet,
+        network,
+        to,
+        show(balance),
+        show(fee),
+        show(amount),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ETH: Denomination = Denomination::ETH;
+    const BTC: Denomination = Denomination::BTC;
+
+    #[test]
+    fn test_format_fixtures_en_and_de() {
+        let cases = [
+            (1_234_567_500_000_000_000_000_000u128, "1,234,567.5 ETH", "1.234.567,5 ETH"),
+            (1_500_000_000_000_000_000, "1.5 ETH", "1,5 ETH"),
+            (21_000_000_000_000, "0.000021 ETH", "0,000021 ETH"),
+            (1, "0.000000000000000001 ETH", "0,000000000000000001 ETH"),
+            (0, "0 ETH", "0 ETH"),
+            (1_000_000_000_000_000_000_000, "1,000 ETH", "1.000 ETH"),
+        ];
+        for (minimal, en, de) in cases {
+            assert_eq!(
+                format_amount(minimal, &ETH, &NumberLocale::EN),
+                format!("{} ({} wei)", en, minimal)
+            );
+            assert_eq!(
+                format_amount(minimal, &ETH, &NumberLocale::for_language("de-DE")),
+                format!("{} ({} wei)", de, minimal)
+            );
+        }
+        assert_eq!(
+            format_amount(123_456_789_012, &BTC, &NumberLocale::EN),
+            "1,234.56789012 BTC (123456789012 sat)"
+        );
+    }
+
+    #[test]
+    fn test_locale_resolution() {
+        let i18n = I18nManager::new("en".to_string());
+        assert_eq!(NumberLocale::resolve(Some("de"), Some("en-US"), &i18n), NumberLocale::DE);
+        assert_eq!(NumberLocale::resolve(None, Some("de_AT"), &i18n), NumberLocale::DE);
+        assert_eq!(NumberLocale::resolve(None, None, &i18n), NumberLocale::EN);
+        assert_eq!(NumberLocale::resolve(None, None, &I18nManager::new("fr".to_string())), NumberLocale::SPACE);
+        assert_eq!(NumberLocale::for_language("zh-CN"), NumberLocale::EN);
+    }
+
+    #[test]
+    fn test_parse_accepts_both_forms() {
+        assert_eq!(parse_amount("1.5eth", &ETH), Ok(1_500_000_000_000_000_000));
+        assert_eq!(parse_amount("1.5 ETH", &ETH), Ok(1_500_000_000_000_000_000));
+        assert_eq!(parse_amount("1500000000000000000wei", &ETH), Ok(1_500_000_000_000_000_000));
+        assert_eq!(parse_amount("2eth", &ETH), Ok(2_000_000_000_000_000_000));
+        assert_eq!(parse_amount("0.25", &ETH), Ok(250_000_000_000_000_000));
+        assert_eq!(parse_amount("1500sats", &BTC), Ok(1500));
+        assert_eq!(parse_amount("0.00001500 btc", &BTC), Ok(1500));
+        assert_eq!(from_decimal("0.000000", &ETH), Ok(0));
+        assert_eq!(from_decimal("0.100000000000000000", &ETH), Ok(100_000_000_000_000_000));
+    }
+
+    #[test]
+    fn test_parse_format_round_trip() {
+        for minimal in [1u128, 21_000_000_000_000, 1_500_000_000_000_000_000, 123_456_789_000_000_000_000_000] {
+            let decimal = to_decimal(minimal, &ETH);
+            assert_eq!(parse_amount(&format!("{}eth", decimal), &ETH), Ok(minimal));
+            assert_eq!(parse_amount(&format!("{}wei", minimal), &ETH), Ok(minimal));
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_ambiguous_and_malformed() {
+        assert!(matches!(parse_amount("1500000000000000000", &ETH), Err(AmountInputError::Ambiguous(..))));
+        assert!(matches!(parse_amount("15", &BTC), Err(AmountInputError::Ambiguous(..))));
+        assert!(matches!(parse_amount("1.5btc", &ETH), Err(AmountInputError::UnknownUnit { .. })));
+        assert!(matches!(parse_amount("1.5wei", &ETH), Err(AmountInputError::FractionalMinimal { .. })));
+        assert!(matches!(parse_amount("0.000000001btc", &BTC), Err(AmountInputError::TooPrecise { .. })));
+        // locale 分隔符不参与输入
+        assert!(matches!(parse_amount("1,5eth", &ETH), Err(AmountInputError::Invalid(_))));
+        assert!(matches!(parse_amount("1,000wei", &ETH), Err(AmountInputError::Invalid(_))));
+        assert!(matches!(parse_amount("0eth", &ETH), Err(AmountInputError::Invalid(_))));
+        assert!(matches!(parse_amount("-1eth", &ETH), Err(AmountInputError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_send_prompt_shows_fee_and_total() {
+        let prompt = send_prompt(
+            &SendPreview {
+                wallet: "hot",
+                network: "eth",
+                to: "0x000000000000000000000000000000000000dEaD",
+                amount: 1_500_000_000_000_000_000,
+                fee: 420_000_000_000_000,
+            },
+            &ETH,
+            &NumberLocale::EN,
+        );
+        assert!(prompt.contains("  Amount: 1.5 ETH (1500000000000000000 wei)\n"));
+        assert!(prompt.contains("  Fee:    0.00042 ETH (420000000000000 wei)\n"));
+        assert!(prompt.contains("  Total:  1.50042 ETH (1500420000000000000 wei)\n"));
+    }
+
+    #[test]
+    fn test_sweep_prompt_snapshot() {
+        let prompt = sweep_prompt(
+            "treasury",
+            "eth",
+            "0x000000000000000000000000000000000000dEaD",
+            12_345_678_900_000_000_000_000,
+            420_000_000_000_000,
+            &ETH,
+            &NumberLocale::DE,
+        )
+        .unwrap();
+        assert_eq!(
+            prompt,
+            "Sweep all funds from wallet 'treasury' on eth\n\
+             \x20 To:      0x000000000000000000000000000000000000dEaD\n\
+             \x20 Balance: 12.345,6789 ETH (12345678900000000000000 wei)\n\
+             \x20 Fee:     0,00042 ETH (420000000000000 wei)\n\
+             \x20 Sends:   12.345,67848 ETH (12345678480000000000000 wei)\n\
+             Proceed? [y/N] "
+        );
+        assert!(sweep_prompt("treasury", "eth", "0x0", 100, 100, &ETH, &NumberLocale::EN).is_none());
+    }
+}
@@ -0,0 +1,439 @@
+//! `hot_wallet client`：通过 HTTP API 管理运行中服务上的托管wallet
+//!
+//! API key 经 `secure_env` 读取（`API_KEY`，已安装密钥后端时从后端读取），
+//! 以 `X-API-KEY` 头发送。wallet Password只从终端交互读取（不回显），不接受命令行参数；
+//! stdin 不是终端时读一行，便于脚本通过管道传入。
+//!
+//! 退出码在 [`super::output`] 的 0/1/2 之上按 HTTP 状态细分：
+//! 400/422 → 2，401/403 → 3，404 → 4，409 → 5，其余（5xx、连接失败）→ 1。
+
+use std::io::{self, BufRead, IsTerminal};
+use std::time::Duration;
+
+use anyhow::anyhow;
+use clap::{Args, Subcommand};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use zeroize::Zeroizing;
+
+use super::amounts::NumberLocale;
+use super::output::{CliError, Output, OutputFormat, Render, EXIT_FAILURE, EXIT_SUCCESS, EXIT_USAGE};
+use crate::api::handlers::custodial::{CustodialBalanceResponse, CustodialWalletCreated, CustodialWalletList};
+use crate::api::types::{PendingApprovalResponse, SendTransactionResponse};
+use crate::security::env_manager::secure_env;
+
+pub const DEFAULT_SERVER: &str = "http://127.0.0.1:8888";
+/// API key 缺失或被拒（401/403）
+pub const EXIT_AUTH: i32 = 3;
+/// wallet等资源不存在（404）
+pub const EXIT_NOT_FOUND: i32 = 4;
+/// 与现有状态冲突，如wallet名已存在（409）
+pub const EXIT_CONFLICT: i32 = 5;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const CLIENT_EXIT_CODES_HELP: &str = "Exit codes:\n  \
+    0  success\n  \
+    1  server error or server unreachable\n  \
+    2  invalid arguments or request (HTTP 400/422), API_KEY not set\n  \
+    3  API key or wallet password rejected (HTTP 401/403)\n  \
+    4  wallet not found (HTTP 404)\n  \
+    5  conflict, e.g. the wallet name is taken (HTTP 409)";
+
+/// Manage custodial wallets on a running server over its HTTP API.
+/// The admin API key is read from API_KEY; wallet passwords are prompted for.
+#[derive(Debug, Args)]
+#[command(after_help = CLIENT_EXIT_CODES_HELP)]
+pub struct ClientArgs {
+    /// Base URL of the server
+    #[arg(long, global = true, default_value = DEFAULT_SERVER)]
+    pub server: String,
+    /// Print the result as one line of JSON instead of a table
+    #[arg(long, global = true)]
+    pub json: bool,
+    #[command(subcommand)]
+    pub command: ClientCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ClientCommand {
+    /// List the wallets held by the server
+    List,
+    /// Create a wallet; the password is prompted for twice
+    Create {
+        name: String,
+        /// Use quantum-safe encryption for the wallet
+        #[arg(long)]
+        quantum_safe: bool,
+    },
+    /// Show a wallet's native balance
+    Balance {
+        name: String,
+        #[arg(long, default_value = "eth")]
+        network: String,
+    },
+    /// Send native coin from a wallet, signed by the server
+    Send {
+        name: String,
+        #[arg(long)]
+        to: String,
+        /// Amount in whole coins, e.g. `0.25`
+        #[arg(long)]
+        amount: String,
+        #[arg(long, default_value = "eth")]
+        network: String,
+    },
+}
+
+/// HTTP 状态 → 退出码
+pub fn exit_code_for_status(status: u16) -> i32 {
+    match status {
+        200..=299 => EXIT_SUCCESS,
+        400 | 422 => EXIT_USAGE,
+        401 | 403 => EXIT_AUTH,
+        404 => EXIT_NOT_FOUND,
+        409 => EXIT_CONFLICT,
+        _ => EXIT_FAILURE,
+    }
+}
+
+/// 错误响应体为 `{"error": {"code", "message"}}`；其他内容（如代理返回的页面）原样输出
+fn error_message(status: u16, body: &str) -> String {
+    let parsed: Option<Value> = serde_json::from_str(body).ok();
+    let (message, code) = match parsed.as_ref().map(|v| &v["error"]) {
+        Some(detail @ Value::Object(_)) => (detail["message"].as_str().unwrap_or_default(), detail["code"].as_str()),
+        _ => (body.trim(), None),
+    };
+    match code {
+        Some(code) => format!("HTTP {} {}: {}", status, code, message),
+        None if message.is_empty() => format!("HTTP {}", status),
+        None => format!("HTTP {}: {}", status, message),
+    }
+}
+
+/// 托管wallet API 的客户端
+pub struct ApiClient {
+    http: reqwest::Client,
+    base: String,
+    api_key: Zeroizing<String>,
+}
+
+impl ApiClient {
+    pub fn new(server: &str, api_key: &str) -> Result<Self, CliError> {
+        let http = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build().map_err(CliError::failure)?;
+        Ok(Self {
+            http,
+            base: server.trim_end_matches('/').to_string(),
+            api_key: Zeroizing::new(api_key.to_string()),
+        })
+    }
+
+    /// API key 来自 `secure_env`；未设置时退出码 2
+    pub fn from_env(server: &str) -> Result<Self, CliError> {
+        let key = secure_env::get_api_key().map_err(CliError::usage)?;
+        let key = std::str::from_utf8(key.as_slice()).map_err(|_| CliError::usage(anyhow!("API_KEY is not UTF-8")))?;
+        Self::new(server, key)
+    }
+
+    async fn request<T: for<'de> Deserialize<'de>>(&self, request: reqwest::RequestBuilder) -> Result<T, CliError> {
+        let response = request
+            .header("X-API-KEY", self.api_key.as_str())
+            .send()
+            .await
+            .map_err(|e| CliError::failure(anyhow!("cannot reach {}: {}", self.base, e)))?;
+        let status = response.status().as_u16();
+        let body = response.text().await.map_err(CliError::failure)?;
+        if !(200..300).contains(&status) {
+            let error = anyhow!(error_message(status, &body));
+            return Err(CliError { exit_code: exit_code_for_status(status), error });
+        }
+        serde_json::from_str(&body).map_err(|e| CliError::failure(anyhow!("unexpected response from server: {}", e)))
+    }
+
+    pub async fn list_wallets(&self) -> Result<CustodialWalletList, CliError> {
+        self.request(self.http.get(format!("{}/api/admin/wallets", self.base))).await
+    }
+
+    pub async fn create_wallet(
+        &self,
+        name: &str,
+        password: &str,
+        quantum_safe: bool,
+    ) -> Result<CustodialWalletCreated, CliError> {
+        let body = serde_json::json!({ "name": name, "password": password, "quantum_safe": quantum_safe });
+        self.request(self.http.post(format!("{}/api/admin/wallets", self.base)).json(&body)).await
+    }
+
+    pub async fn balance(
+        &self,
+        name: &str,
+        network: &str,
+        password: &str,
+    ) -> Result<CustodialBalanceResponse, CliError> {
+        let body = serde_json::json!({ "network": network, "password": password });
+        self.request(self.http.post(format!("{}/api/admin/wallets/{}/balance", self.base, name)).json(&body)).await
+    }
+
+    /// 服务端sign发送；超过审查阈值时服务端返回 202，结果为待审批
+    pub async fn send(
+        &self,
+        name: &str,
+        to: &str,
+        amount: &str,
+        network: &str,
+        password: &str,
+    ) -> Result<ClientSendResult, CliError> {
+        let body = serde_json::json!({
+            "wallet_name": name,
+            "to": to,
+            "amount": amount,
+            "network": network,
+            "password": password,
+        });
+        let url = format!("{}/api/transactions/send", self.base);
+        let result: Value = self.request(self.http.post(url).json(&body)).await?;
+        let unexpected = |e: serde_json::Error| CliError::failure(anyhow!("unexpected response from server: {}", e));
+        if result.get("approval_id").is_some() {
+            let held: PendingApprovalResponse = serde_json::from_value(result).map_err(unexpected)?;
+            return Ok(ClientSendResult::PendingApproval { approval_id: held.approval_id, expires_at: held.expires_at });
+        }
+        let sent: SendTransactionResponse = serde_json::from_value(result).map_err(unexpected)?;
+        Ok(ClientSendResult::Sent { tx_hash: sent.tx_hash, explorer_url: sent.explorer_url })
+    }
+}
+
+// ---- 结果渲染：text 为表格 ----
+
+/// 左对齐的等宽表格，列间两个空格
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells.iter().zip(&widths).map(|(c, w)| format!("{:<w$}", c, w = *w)).collect();
+        padded.join("  ").trim_end().to_string()
+    };
+    let mut lines = vec![line(headers.to_vec())];
+    lines.extend(rows.iter().map(|row| line(row.iter().map(String::as_str).collect())));
+    lines.join("\n")
+}
+
+/// 单条记录：两列的字段/值表
+fn record(fields: &[(&str, String)]) -> String {
+    let rows: Vec<Vec<String>> = fields.iter().map(|(k, v)| vec![k.to_string(), v.clone()]).collect();
+    table(&["FIELD", "VALUE"], &rows)
+}
+
+impl Render for CustodialWalletList {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        let rows: Vec<Vec<String>> = self
+            .wallets
+            .iter()
+            .map(|w| {
+                vec![
+                    w.name.clone(),
+                    w.quantum_safe.to_string(),
+                    w.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    w.allowed_networks.as_ref().map_or_else(|| "all".to_string(), |n| n.join(",")),
+                ]
+            })
+            .collect();
+        table(&["NAME", "QUANTUM_SAFE", "CREATED", "NETWORKS"], &rows)
+    }
+}
+
+impl Render for CustodialWalletCreated {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        record(&[
+            ("name", self.name.clone()),
+            ("address", self.address.clone()),
+            ("quantum_safe", self.quantum_safe.to_string()),
+        ])
+    }
+}
+
+impl Render for CustodialBalanceResponse {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        record(&[
+            ("name", self.name.clone()),
+            ("network", self.network.clone()),
+            ("address", self.address.clone()),
+            ("balance", self.balance.clone()),
+        ])
+    }
+}
+
+/// `client send` 的结果：已广播，或超过审查阈值等待第二人审批
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ClientSendResult {
+    Sent {
+        tx_hash: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        explorer_url: Option<String>,
+    },
+    PendingApproval {
+        approval_id: String,
+        expires_at: i64,
+    },
+}
+
+impl Render for ClientSendResult {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        match self {
+            Self::Sent { tx_hash, explorer_url } => {
+                let mut fields = vec![("status", "sent".to_string()), ("tx_hash", tx_hash.clone())];
+                if let Some(url) = explorer_url {
+                    fields.push(("explorer", url.clone()));
+                }
+                record(&fields)
+            }
+            Self::PendingApproval { approval_id, expires_at } => record(&[
+                ("status", "pending_approval".to_string()),
+                ("approval_id", approval_id.clone()),
+                ("expires_at", expires_at.to_string()),
+            ]),
+        }
+    }
+}
+
+// ---- Password输入 ----
+
+/// 在 stderr 上提示并读取Password：终端上关闭回显，否则读 stdin 的一行
+///
+/// 无法关闭回显时报错而不是明文回显。
+pub fn prompt_password(output: &Output, prompt: &str) -> Result<Zeroizing<String>, CliError> {
+    let stdin = io::stdin();
+    let tty = stdin.is_terminal();
+    output.prompt(prompt)?;
+    let echo_off = if tty {
+        match EchoGuard::disable() {
+            Some(guard) => Some(guard),
+            None => {
+                eprintln!();
+                return Err(CliError::failure(anyhow!("cannot turn off terminal echo for the password prompt")));
+            }
+        }
+    } else {
+        None
+    };
+    let mut line = Zeroizing::new(String::new());
+    let read = stdin.lock().read_line(&mut line);
+    if let Some(guard) = echo_off {
+        drop(guard);
+        eprintln!();
+    }
+    if read? == 0 {
+        return Err(CliError::usage(anyhow!("no password given")));
+    }
+    let password = Zeroizing::new(line.trim_end_matches(['\r', '\n']).to_string());
+    if password.is_empty() {
+        return Err(CliError::usage(anyhow!("the password must not be empty")));
+    }
+    Ok(password)
+}
+
+/// 关闭回显前由 `stty -g` 保存的终端设置；恢复后清空
+static SAVED_TTY: parking_lot::Mutex<Option<String>> = parking_lot::Mutex::new(None);
+
+/// 关闭回显期间持有；drop 时恢复原终端设置，Ctrl-C 时先恢复再以 130 退出
+struct EchoGuard;
+
+impl EchoGuard {
+    /// 经 `stty` 关闭继承来的终端的回显；没有 `stty` 的平台返回 None
+    fn disable() -> Option<Self> {
+        if !cfg!(unix) {
+            return None;
+        }
+        let saved = stty(&["-g"])?;
+        *SAVED_TTY.lock() = Some(saved.trim().to_string());
+        restore_on_interrupt();
+        if stty(&["-echo"]).is_none() {
+            restore_tty();
+            return None;
+        }
+        Some(Self)
+    }
+}
+
+impl Drop for EchoGuard {
+    fn drop(&mut self) {
+        restore_tty();
+    }
+}
+
+fn restore_tty() {
+    if let Some(saved) = SAVED_TTY.lock().take() {
+        stty(&[saved.as_str()]);
+    }
+}
+
+/// Ctrl-C 默认直接结束进程，drop 来不及恢复回显；进程内只装一次
+fn restore_on_interrupt() {
+    static INSTALLED: std::sync::Once = std::sync::Once::new();
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    INSTALLED.call_once(|| {
+        runtime.spawn(async {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                restore_tty();
+                eprintln!();
+                std::process::exit(130);
+            }
+        });
+    });
+}
+
+/// 以继承来的终端为 stdin 运行 `stty`，成功时返回它的 stdout
+fn stty(args: &[&str]) -> Option<String> {
+    let out = std::process::Command::new("stty")
+        .args(args)
+        .stdin(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    out.status.success().then(|| String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+// ---- 入口 ----
+
+/// 执行一条 `client` 子命令，返回退出码；错误已写到 stderr
+pub async fn execute(args: ClientArgs) -> i32 {
+    let format = if args.json { OutputFormat::Json } else { OutputFormat::Text };
+    let output = Output { format, quiet: false, locale: NumberLocale::EN };
+    match run(args, &output).await {
+        Ok(()) => EXIT_SUCCESS,
+        Err(e) => {
+            output.error(&e);
+            e.exit_code
+        }
+    }
+}
+
+async fn run(args: ClientArgs, output: &Output) -> Result<(), CliError> {
+    let client = ApiClient::from_env(&args.server)?;
+    match args.command {
+        ClientCommand::List => output.emit(&client.list_wallets().await?)?,
+        ClientCommand::Create { name, quantum_safe } => {
+            let password = prompt_password(output, &format!("New password for '{}': ", name))?;
+            let confirmed = prompt_password(output, "Repeat password: ")?;
+            if password != confirmed {
+                return Err(CliError::usage(anyhow!("passwords do not match")));
+            }
+            output.emit(&client.create_wallet(&name, &password, quantum_safe).await?)?
+        }
+        ClientCommand::Balance { name, network } => {
+            let password = prompt_password(output, &format!("Password for '{}': ", name))?;
+            output.emit(&client.balance(&name, &network, &password).await?)?
+        }
+        ClientCommand::Send { name, to, amount, network } => {
+            let password = prompt_password(output, &format!("Password for '{}': ", name))?;
+            output.emit(&client.send(&name, &to, &amount, &network, &password).await?)?
+        }
+    }
+    Ok(())
+}
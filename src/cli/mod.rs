@@ -0,0 +1,265 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+pub mod amounts;
+pub mod client;
+pub mod output;
+
+use output::OutputFormat;
+
+const EXIT_CODES_HELP: &str = "Exit codes:\n  \
+    0  success\n  \
+    1  operation failed (RPC, signing, unknown wallet, refused broadcast)\n  \
+    2  configuration or validation error (missing env var, bad amount, network or raw transaction)";
+
+/// DeFi Hot Wallet CLI (library-facing definitions)
+#[derive(Debug, Parser)]
+#[command(
+    name = "wallet-cli",
+    about = "DeFi Hot Wallet CLI",
+    disable_help_subcommand = true,
+    after_help = EXIT_CODES_HELP
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Commands,
+    /// Language used to format amounts (e.g. `en`, `de-DE`); defaults to `LANG`
+    #[arg(long, global = true)]
+    pub locale: Option<String>,
+    /// Skip the confirmation prompt of `transfer` and `sweep`
+    #[arg(long, global = true)]
+    pub yes: bool,
+    /// Result format on stdout; diagnostics always go to stderr
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    /// Shorthand for `--format json`
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Only print the result and errors
+    #[arg(long, short, global = true)]
+    pub quiet: bool,
+}
+
+impl Cli {
+    pub fn output_format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else {
+            self.format
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Create a wallet and print its address.
+    /// Exits 2 if the name is taken or invalid.
+    Create {
+        /// Wallet name
+        #[arg(long)]
+        name: String,
+        /// Optional output path
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Show a wallet loaded in this process. Exits 1 if there is none by that name.
+    Info {
+        #[arg(long)]
+        name: String,
+    },
+    /// Send ETH on mainnet. The amount needs a unit unless it has a decimal
+    /// point: `1.5eth`, `1500000000000000000wei`, `0.25`.
+    /// Exits 2 for a bad amount or missing WALLET_PASSWORD, 1 if fee
+    /// estimation or the send fails.
+    Transfer {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        to: String,
+        #[arg(long)]
+        amount: String,
+    },
+    /// Send the whole native balance minus the network fee.
+    /// Exits 2 for an unsupported network or missing WALLET_PASSWORD, 1 if
+    /// the balance does not cover the fee or the send fails.
+    Sweep {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        to: String,
+        #[arg(long, default_value = "eth")]
+        network: String,
+    },
+    /// Print the native balance. Exits 2 for an unsupported network or
+    /// missing WALLET_PASSWORD, 1 if the query fails.
+    Balance {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        network: Option<String>,
+    },
+    /// Not available from the CLI (use the API server); always exits 1.
+    Bridge {
+        #[arg(long = "name")]
+        name: String,
+        #[arg(long = "from-chain")]
+        from_chain: String,
+        #[arg(long = "to-chain")]
+        to_chain: String,
+        #[arg(long)]
+        token: String,
+        #[arg(long)]
+        amount: String,
+    },
+    /// Import a wallet from an Ethereum keystore V3 file.
+    /// Passwords are read from KEYSTORE_PASSWORD and WALLET_PASSWORD.
+    /// Exits 2 if a password or the file is missing, 1 if decryption fails.
+    ImportKeystore {
+        #[arg(long)]
+        name: String,
+        /// Keystore JSON file
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// Export a wallet as an Ethereum keystore V3 file.
+    /// Passwords are read from WALLET_PASSWORD and EXPORT_PASSWORD.
+    /// Exits 2 if a password is missing, 1 if the wallet is unknown or the
+    /// file cannot be written.
+    ExportKeystore {
+        #[arg(long)]
+        name: String,
+        /// Destination file (written with 0600 permissions)
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Print the account xpub and output descriptors of a Bitcoin HD wallet for
+    /// watch-only tools. The password is read from WALLET_PASSWORD.
+    /// Exits 2 for an unknown address type or missing WALLET_PASSWORD, 1 if the
+    /// wallet is unknown or holds an imported key.
+    BtcDescriptor {
+        #[arg(long)]
+        name: String,
+        /// legacy (BIP44), segwit (BIP84) or taproot (BIP86)
+        #[arg(long = "type", default_value = "segwit")]
+        address_type: String,
+        #[arg(long, default_value_t = 0)]
+        account: u32,
+    },
+    /// Decode a signed raw transaction (legacy or typed) and print it as JSON,
+    /// including the sender recovered from the signature.
+    /// Exits 2 if the input is missing or not a signed transaction.
+    DecodeTx {
+        /// `0x` hex; read from `--file` when omitted
+        #[arg(long)]
+        raw: Option<String>,
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Broadcast an externally signed raw transaction. Refuses transactions
+    /// signed for another chain, without EIP-155 replay protection, or (when
+    /// DATABASE_URL points at the wallet database) reusing a nonce the
+    /// server already signed. Exits 2 for chain id and replay protection
+    /// violations, 1 for nonce conflicts and RPC failures.
+    Broadcast {
+        /// File holding the `0x` hex transaction
+        #[arg(long = "raw-tx-file")]
+        raw_tx_file: PathBuf,
+        #[arg(long, default_value = "eth")]
+        network: String,
+        /// Accept legacy signatures without a chain id, which are valid on every chain
+        #[arg(long)]
+        allow_unprotected: bool,
+    },
+    /// Re-seal every wallet in DATABASE_URL from the KEK in WALLET_ENC_KEY to
+    /// the one in NEW_WALLET_ENC_KEY, optionally switching the envelope mode.
+    /// Nothing is written unless every wallet passes; `--dry-run` reports the
+    /// same result without writing.
+    /// Exits 2 if an env var or key is missing or invalid, 1 if any wallet failed.
+    RotateKek {
+        /// `classical` or `quantum`; keeps each wallet's mode when omitted
+        #[arg(long)]
+        to: Option<crate::ops::envelope_rotation::EnvelopeMode>,
+        /// Recorded on every wallet; defaults to a fingerprint of the new KEK
+        #[arg(long)]
+        kek_id: Option<String>,
+        /// Probe and report without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Import the on-chain history of a wallet in DATABASE_URL on one network
+    /// (transactions, ERC-20 transfers and ledger entries), running in the
+    /// foreground until the current head. Each batch is committed with its
+    /// progress, so an interrupted run resumes where it stopped and re-running
+    /// never duplicates records.
+    /// Exits 2 if DATABASE_URL is missing or the network is unknown, 1 if the
+    /// wallet is not registered on the network or the chain cannot be read.
+    Backfill {
+        #[arg(long)]
+        wallet: String,
+        #[arg(long, default_value = "eth")]
+        network: String,
+        /// First block to read; genesis when omitted
+        #[arg(long)]
+        from_block: Option<u64>,
+    },
+    /// Compare the transactions stored in DATABASE_URL and created between
+    /// --from and --to (Unix seconds) with their on-chain receipts, and look
+    /// for transactions of the stored wallets' addresses mined in that range
+    /// without a record. Runs in the foreground and prints every discrepancy.
+    /// With --apply, pending statuses, missing fees and missing records are
+    /// corrected; anything else is only reported.
+    /// Exits 2 if DATABASE_URL is missing, a network is unknown or the range
+    /// is empty, 1 if the chain cannot be read.
+    Reconcile {
+        #[arg(long)]
+        from: i64,
+        #[arg(long)]
+        to: i64,
+        /// Network to check; repeat for several, all known networks when omitted
+        #[arg(long = "network")]
+        networks: Vec<String>,
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Import or export a wallet's address book as CSV (`label,address,network,tags`).
+    /// Works on the database at DATABASE_URL.
+    #[command(subcommand)]
+    AddressBook(AddressBookCommand),
+    /// List wallets loaded in this process
+    List,
+    /// Generate a mnemonic. It is only shown with ALLOW_PLAINTEXT_MNEMONIC=1
+    /// and ALLOW_PLAINTEXT_MNEMONIC_CONFIRM=1, or written encrypted when
+    /// MNEMONIC_EXPORT_KEY is set. Exits 2 for an invalid MNEMONIC_EXPORT_KEY,
+    /// 1 if plaintext display is refused.
+    GenerateMnemonic,
+    Help,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AddressBookCommand {
+    /// Validate a CSV file against the address book and apply it. Invalid rows
+    /// are reported and skipped unless `--strict` is given.
+    /// Exits 2 if DATABASE_URL is missing or the CSV is malformed, 1 if the
+    /// wallet is unknown or `--strict` refused the file.
+    Import {
+        #[arg(long)]
+        wallet: String,
+        #[arg(long)]
+        file: PathBuf,
+        /// Print the diff without writing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Write nothing if any row is invalid
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Write the address book as canonical CSV, sorted by network and address.
+    /// Exits 2 if DATABASE_URL is missing, 1 if the wallet is unknown.
+    Export {
+        #[arg(long)]
+        wallet: String,
+        /// Destination file; stdout when omitted
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
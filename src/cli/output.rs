@@ -0,0 +1,505 @@
+//! CLI 结果输出与退出码
+//!
+//! 每个子命令把结果收成一个可序列化的结构体，按 `--format` 渲染：
+//! - `text`：面向人的原有格式
+//! - `json`：stdout 上单行 JSON，可直接反序列化为本模块的结构体
+//!
+//! stdout 只写结果；提示、确认、日志和错误一律写 stderr，`--quiet` 时只保留错误。
+//!
+//! 退出码：0 成功；1 操作失败（RPC、签名、wallet不存在等）；
+//! 2 配置或参数错误（缺少环境变量、金额/network/原始transaction无效）。
+
+use std::fmt;
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use super::amounts::{format_amount, Denomination, NumberLocale};
+use crate::blockchain::ethereum::raw_tx::DecodedRawTransaction;
+use crate::core::address_book::{ImportCounts, ImportPlan};
+use crate::core::errors::WalletError;
+use crate::storage::{LedgerBalance, ScanCursor};
+
+pub const EXIT_SUCCESS: i32 = 0;
+/// 操作本身失败
+pub const EXIT_FAILURE: i32 = 1;
+/// 配置或参数错误；与 clap 的用法错误一致
+pub const EXIT_USAGE: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// 子命令结果：人读格式或单行 JSON
+pub trait Render: Serialize {
+    /// 人读格式；空串表示 stdout 不输出
+    fn render_text(&self, locale: &NumberLocale) -> String;
+}
+
+/// 一次 CLI 调用的输出设置
+#[derive(Debug, Clone, Copy)]
+pub struct Output {
+    pub format: OutputFormat,
+    pub quiet: bool,
+    pub locale: NumberLocale,
+}
+
+impl Output {
+    /// 结果的 stdout 文本（不含换行）
+    pub fn render<R: Render>(&self, result: &R) -> String {
+        match self.format {
+            OutputFormat::Text => result.render_text(&self.locale),
+            OutputFormat::Json => serde_json::to_string(result).unwrap_or_else(|e| {
+                serde_json::json!({ "error": format!("failed to serialize result: {}", e) }).to_string()
+            }),
+        }
+    }
+
+    pub fn emit<R: Render>(&self, result: &R) -> io::Result<()> {
+        let rendered = self.render(result);
+        if rendered.is_empty() {
+            return Ok(());
+        }
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{}", rendered)?;
+        stdout.flush()
+    }
+
+    /// 非必要提示，`--quiet` 时省略
+    pub fn note(&self, message: impl fmt::Display) {
+        if !self.quiet {
+            eprintln!("{}", message);
+        }
+    }
+
+    /// 交互提示（不换行），总是写 stderr
+    pub fn prompt(&self, message: &str) -> io::Result<()> {
+        let mut stderr = io::stderr().lock();
+        write!(stderr, "{}", message)?;
+        stderr.flush()
+    }
+
+    /// 失败时写 stderr：JSON 模式为 `{"error", "exit_code"}` 单行
+    pub fn error(&self, error: &CliError) {
+        match self.format {
+            OutputFormat::Text => eprintln!("error: {:#}", error.error),
+            OutputFormat::Json => eprintln!(
+                "{}",
+                serde_json::json!({ "error": format!("{:#}", error.error), "exit_code": error.exit_code })
+            ),
+        }
+    }
+}
+
+/// 带退出码的 CLI 错误；`?` 转换来的错误默认算操作失败
+#[derive(Debug)]
+pub struct CliError {
+    pub exit_code: i32,
+    pub error: anyhow::Error,
+}
+
+impl CliError {
+    pub fn failure(error: impl Into<anyhow::Error>) -> Self {
+        Self { exit_code: EXIT_FAILURE, error: error.into() }
+    }
+
+    pub fn usage(error: impl Into<anyhow::Error>) -> Self {
+        Self { exit_code: EXIT_USAGE, error: error.into() }
+    }
+
+    /// wallet层的参数/配置错误归为 2，其余为 1
+    pub fn from_wallet(error: WalletError) -> Self {
+        match error {
+            WalletError::ValidationError(_) | WalletError::ConfigError(_) => Self::usage(error),
+            other => Self::failure(other),
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#}", self.error)
+    }
+}
+
+impl From<anyhow::Error> for CliError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::failure(error)
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        Self::failure(error)
+    }
+}
+
+/// 把任意错误标成配置/参数错误（退出码 2）
+pub trait UsageError<T> {
+    fn or_usage(self) -> Result<T, CliError>;
+}
+
+impl<T, E: Into<anyhow::Error>> UsageError<T> for Result<T, E> {
+    fn or_usage(self) -> Result<T, CliError> {
+        self.map_err(CliError::usage)
+    }
+}
+
+// ---- 各子命令的结果 ----
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletCreated {
+    pub name: String,
+    pub network: String,
+    pub address: String,
+    /// 写入了 `--output` 文件时的路径
+    pub output: Option<String>,
+}
+
+impl Render for WalletCreated {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        format!("Created wallet '{}' ({} address {})", self.name, self.network, self.address)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletImported {
+    pub name: String,
+    pub address: String,
+}
+
+impl Render for WalletImported {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        self.address.clone()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreExported {
+    pub name: String,
+    pub path: String,
+}
+
+impl Render for KeystoreExported {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        format!("Keystore of '{}' written to {}", self.name, self.path)
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+impl Render for crate::core::wallet_manager::btc_descriptor::BitcoinDescriptorExport {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        let mut text = format!(
+            "Fingerprint: {}\nPath:        {}\nXpub:        {}",
+            self.fingerprint, self.derivation_path, self.xpub
+        );
+        if let Some(zpub) = &self.zpub {
+            text.push_str(&format!("\nZpub:        {}", zpub));
+        }
+        text.push_str(&format!("\nReceive:     {}\nChange:      {}", self.descriptor, self.change_descriptor));
+        text
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletSummary {
+    pub name: String,
+    /// RFC 3339
+    pub created_at: String,
+    pub quantum_safe: bool,
+    pub multi_sig_threshold: u8,
+    pub networks: Vec<String>,
+}
+
+impl Render for WalletSummary {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        format!(
+            "Name:        {}\nCreated:     {}\nQuantum:     {}\nThreshold:   {}\nNetworks:    {}",
+            self.name,
+            self.created_at,
+            self.quantum_safe,
+            self.multi_sig_threshold,
+            self.networks.join(", ")
+        )
+    }
+}
+
+impl From<crate::core::wallet_info::WalletInfo> for WalletSummary {
+    fn from(info: crate::core::wallet_info::WalletInfo) -> Self {
+        Self {
+            name: info.name,
+            created_at: info.created_at.to_rfc3339(),
+            quantum_safe: info.quantum_safe,
+            multi_sig_threshold: info.multi_sig_threshold,
+            networks: info.networks,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletList {
+    pub wallets: Vec<WalletSummary>,
+}
+
+impl Render for WalletList {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        self.wallets.iter().map(|w| format!("{}\t{}", w.name, w.networks.join(","))).collect::<Vec<_>>().join("\n")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceResult {
+    pub wallet: String,
+    pub network: String,
+    /// 最小单位（wei / sat）
+    pub amount: String,
+    /// 主单位十进制
+    pub decimal: String,
+    pub symbol: String,
+}
+
+impl Render for BalanceResult {
+    fn render_text(&self, locale: &NumberLocale) -> String {
+        match (Denomination::for_network(&self.network), self.amount.parse::<u128>()) {
+            (Some(denom), Ok(minimal)) => format_amount(minimal, &denom, locale),
+            _ => format!("{} {}", self.decimal, self.symbol),
+        }
+    }
+}
+
+/// `submitted` 或 `cancelled`（确认提示被拒绝）
+pub const SEND_SUBMITTED: &str = "submitted";
+pub const SEND_CANCELLED: &str = "cancelled";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendResult {
+    pub wallet: String,
+    pub network: String,
+    pub to: String,
+    /// 最小单位
+    pub amount: String,
+    /// 预估手续费，最小单位
+    pub fee: String,
+    pub status: String,
+    pub tx_hash: Option<String>,
+}
+
+impl Render for SendResult {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        self.tx_hash.clone().unwrap_or_else(|| "Cancelled".to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MnemonicResult {
+    pub word_count: usize,
+    /// 仅在显式允许明文输出时出现
+    pub mnemonic: Option<String>,
+    /// 加密导出文件（`MNEMONIC_EXPORT_KEY`）
+    pub export_path: Option<String>,
+}
+
+impl Render for MnemonicResult {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        match (&self.mnemonic, &self.export_path) {
+            (Some(mnemonic), _) => mnemonic.clone(),
+            (None, Some(path)) => format!("Encrypted mnemonic exported to {}", path),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+impl Render for DecodedRawTransaction {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BroadcastResult {
+    pub network: String,
+    pub tx_hash: String,
+    pub from: String,
+    pub nonce: String,
+}
+
+impl Render for BroadcastResult {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        self.tx_hash.clone()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressBookImported {
+    pub wallet: String,
+    pub dry_run: bool,
+    pub strict: bool,
+    pub applied: bool,
+    pub counts: ImportCounts,
+    #[serde(flatten)]
+    pub plan: ImportPlan,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackfillResult {
+    pub wallet: String,
+    pub network: String,
+    pub cursor: ScanCursor,
+    /// 已回填账目按币种汇总（最小单位）
+    pub balances: Vec<LedgerBalance>,
+}
+
+impl Render for BackfillResult {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        let cursor = &self.cursor;
+        let mut lines = vec![format!(
+            "Backfilled '{}' on {}: blocks {}..={} via {}, {} ledger entries",
+            self.wallet, self.network, cursor.from_block, cursor.target_block, cursor.source, cursor.entries
+        )];
+        lines.extend(self.balances.iter().map(|b| {
+            format!("  {}: net {} (received {}, sent {}, fees {})", b.token, b.net, b.received, b.sent, b.fees)
+        }));
+        lines.join("\n")
+    }
+}
+
+impl Render for AddressBookImported {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        let counts = &self.counts;
+        let verb = if self.applied { "Imported into" } else { "Would import into" };
+        let mut lines = vec![format!(
+            "{} '{}': {} added, {} updated, {} duplicates skipped, {} invalid",
+            verb, self.wallet, counts.would_add, counts.would_update_label, counts.would_skip_duplicate, counts.invalid
+        )];
+        lines.extend(self.plan.invalid.iter().map(|row| format!("  line {}: {}", row.line, row.reason)));
+        if self.strict && counts.invalid > 0 {
+            lines.push("Nothing written: --strict and the file has invalid rows".to_string());
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressBookExported {
+    pub wallet: String,
+    pub entries: usize,
+    /// 写入了 `--output` 文件时的路径
+    pub path: Option<String>,
+    /// 未指定 `--output` 时输出到 stdout 的 CSV
+    pub csv: Option<String>,
+}
+
+impl Render for AddressBookExported {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        match (&self.csv, &self.path) {
+            (Some(csv), _) => csv.trim_end().to_string(),
+            (None, Some(path)) => format!("Exported {} address book entries of '{}' to {}", self.entries, self.wallet, path),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+impl Render for crate::core::wallet_manager::reconcile::ReconciliationReport {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        let totals = &self.totals;
+        let mut lines = vec![format!(
+            "Reconciled {}..{} on {}: {} records checked, {} on-chain transactions scanned, {} not mined yet",
+            self.range.from,
+            self.range.to,
+            self.networks.join(", "),
+            totals.checked,
+            totals.scanned,
+            totals.unmined
+        )];
+        lines.push(if self.apply {
+            format!("{} discrepancies, {} corrected", totals.discrepancies, totals.applied)
+        } else {
+            format!("{} discrepancies (report only)", totals.discrepancies)
+        });
+        lines.extend(self.discrepancies.iter().map(|d| {
+            // 已修正的标 fixed，可修正但未修正的标 fixable
+            let mark = match (d.applied, d.correctable) {
+                (true, _) => " [fixed]",
+                (false, true) => " [fixable]",
+                (false, false) => "",
+            };
+            format!("  {} {} {} {}: {}{}", d.kind.as_str(), d.network, d.wallet_id, d.tx_hash, d.detail, mark)
+        }));
+        lines.join("\n")
+    }
+}
+
+impl Render for crate::ops::envelope_rotation::RotationReport {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        let totals = &self.totals;
+        let verb = match (self.dry_run, self.applied) {
+            (_, true) => "Rotated",
+            (true, false) => "Dry run: would rotate",
+            (false, false) => "Nothing written: could not rotate",
+        };
+        let mut lines = vec![format!(
+            "{} {} wallets to {}: {} ok, {} failed ({} decrypt, {} blocked), {} mode changes",
+            verb,
+            totals.wallets,
+            self.kek_id,
+            totals.succeeded,
+            totals.failed,
+            totals.decrypt_failed,
+            totals.blocked,
+            totals.mode_changes
+        )];
+        lines.extend(self.wallets.iter().filter_map(|w| w.error.as_ref().map(|e| format!("  {}: {}", w.wallet, e))));
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HelpText {
+    pub usage: String,
+}
+
+impl Render for HelpText {
+    fn render_text(&self, _: &NumberLocale) -> String {
+        self.usage.trim_end().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(format: OutputFormat) -> Output {
+        Output { format, quiet: false, locale: NumberLocale::DE }
+    }
+
+    #[test]
+    fn test_json_is_one_line_and_roundtrips() {
+        let balance = BalanceResult {
+            wallet: "main".to_string(),
+            network: "eth".to_string(),
+            amount: "1234500000000000000000".to_string(),
+            decimal: "1234.5".to_string(),
+            symbol: "ETH".to_string(),
+        };
+        let json = output(OutputFormat::Json).render(&balance);
+        assert!(!json.contains('\n'));
+        assert_eq!(serde_json::from_str::<BalanceResult>(&json).unwrap(), balance);
+        assert_eq!(
+            output(OutputFormat::Text).render(&balance),
+            "1.234,5 ETH (1234500000000000000000 wei)"
+        );
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let missing: Result<(), std::env::VarError> = Err(std::env::VarError::NotPresent);
+        assert_eq!(missing.or_usage().unwrap_err().exit_code, EXIT_USAGE);
+        assert_eq!(CliError::from(anyhow::anyhow!("rpc down")).exit_code, EXIT_FAILURE);
+        assert_eq!(CliError::from_wallet(WalletError::ValidationError("bad".into())).exit_code, EXIT_USAGE);
+        assert_eq!(CliError::from_wallet(WalletError::NotFoundError("w".into())).exit_code, EXIT_FAILURE);
+    }
+}
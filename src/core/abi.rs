@@ -24,15 +24,24 @@ pub fn abi_word_address(addr_hex: &str) -> Result<[u8; 32]> {
 }
 
 /// Encode a decimal string into a 32-byte big-endian unsigned ABI word.
+/// Accepts the full uint256 range.
 pub fn abi_word_uint256_from_str(value: &str) -> Result<[u8; 32]> {
     if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
         return Err(anyhow::anyhow!("Uint256 must be a non-empty integer string"));
     }
-    let v = value
-        .parse::<u128>()
-        .map_err(|_| anyhow::anyhow!("Uint256 value out of supported range (<= u128 for now)"))?;
     let mut out = [0u8; 32];
-    out[16..].copy_from_slice(&v.to_be_bytes());
+    for digit in value.bytes().map(|b| b - b'0') {
+        // out = out * 10 + digit, least significant byte first
+        let mut carry = u16::from(digit);
+        for byte in out.iter_mut().rev() {
+            let v = u16::from(*byte) * 10 + carry;
+            *byte = v as u8;
+            carry = v >> 8;
+        }
+        if carry != 0 {
+            return Err(anyhow::anyhow!("Uint256 value out of range (> 2^256 - 1)"));
+        }
+    }
     Ok(out)
 }
 
@@ -46,6 +55,25 @@ pub fn abi_pack(selector: [u8; 4], words: &[[u8; 32]]) -> Vec<u8> {
     out
 }
 
+/// Encode a `u64` as a 32-byte big-endian ABI word (lengths, offsets, small integers).
+pub fn abi_word_u64(value: u64) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out[24..].copy_from_slice(&value.to_be_bytes());
+    out
+}
+
+/// Encode the tail of a dynamic `bytes` value: length word followed by the
+/// data right-padded to a multiple of 32 bytes. The caller writes the offset
+/// word pointing here in the head.
+pub fn abi_encode_bytes(data: &[u8]) -> Vec<u8> {
+    let padded = data.len().div_ceil(32) * 32;
+    let mut out = Vec::with_capacity(32 + padded);
+    out.extend_from_slice(&abi_word_u64(data.len() as u64));
+    out.extend_from_slice(data);
+    out.resize(32 + padded, 0);
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,7 +101,11 @@ mod tests {
         assert!(word[..31].iter().all(|&b| b == 0));
         assert_eq!(word[31], 42);
         let big = abi_word_uint256_from_str("340282366920938463463374607431768211455"); // u128::MAX
-        assert!(big.is_ok());
+        assert_eq!(big.unwrap()[16..], u128::MAX.to_be_bytes());
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(abi_word_uint256_from_str(max).unwrap(), [0xff; 32]);
+        let err = abi_word_uint256_from_str(&max.replace("935", "936")).unwrap_err();
+        assert!(err.to_string().contains("out of range"));
         let err = abi_word_uint256_from_str("").unwrap_err();
         assert!(err.to_string().contains("non-empty"));
         let err = abi_word_uint256_from_str("1.0").unwrap_err();
@@ -91,4 +123,16 @@ mod tests {
         assert_eq!(&data[4..36], &addr);
         assert_eq!(&data[36..68], &amt);
     }
+
+    #[test]
+    fn test_abi_encode_bytes_padding() {
+        assert_eq!(abi_encode_bytes(&[]), abi_word_u64(0).to_vec());
+        let enc = abi_encode_bytes(&[0xab; 33]);
+        assert_eq!(enc.len(), 32 + 64);
+        assert_eq!(enc[31], 33);
+        assert!(enc[32..65].iter().all(|&b| b == 0xab));
+        assert!(enc[65..].iter().all(|&b| b == 0));
+        // exact multiple of 32: no extra padding word
+        assert_eq!(abi_encode_bytes(&[1; 32]).len(), 64);
+    }
 }
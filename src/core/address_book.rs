@@ -0,0 +1,367 @@
+//! wallet address book 的 CSV 导入/导出
+//!
+//! 列由表头决定（顺序不限）：`label`、`address`、`network` 必填，`tags` 可选，
+//! 多个 tag 用 `;` 分隔。每行单独校验：network 走 [`NetworkName`] 规范化，
+//! address 走链无关的 [`detect_address`]，必须能用在该 network 上；EVM
+//! address 的 EIP-55 校验和写错的拒绝，保存规范化后的形式。
+//!
+//! 导入先对照现有 address book 算出 [`ImportPlan`]，dry-run 直接返回，提交时
+//! 再整体写入。导出按 (network, address) 排序，同一 address book 导出结果逐字节
+//! 一致，导出再导入不产生任何变更。
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::validators::NetworkName;
+use crate::core::validation::detect_address;
+use crate::util::csv::{write_record, CsvError, CsvReader, CsvRecord};
+
+pub const MAX_LABEL_LEN: usize = 64;
+pub const MAX_TAGS: usize = 16;
+/// 单次导入的行数上限（不含表头）
+pub const MAX_IMPORT_ROWS: usize = 50_000;
+
+/// 导出的表头，也是导入的列名
+pub const COLUMNS: [&str; 4] = ["label", "address", "network", "tags"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressBookEntry {
+    /// 规范名（`eth`、`polygon`、`btc` …）
+    pub network: String,
+    /// EIP-55 / 规范化后的 address
+    pub address: String,
+    pub label: String,
+    /// 去重、排序后的 tag
+    pub tags: Vec<String>,
+}
+
+impl AddressBookEntry {
+    fn key(&self) -> (String, String) {
+        (self.network.clone(), self.address.clone())
+    }
+}
+
+/// 将新增的行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedAdd {
+    pub line: usize,
+    #[serde(flatten)]
+    pub entry: AddressBookEntry,
+}
+
+/// 已有 address，label 或 tags 不同
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedUpdate {
+    pub line: usize,
+    #[serde(flatten)]
+    pub entry: AddressBookEntry,
+    pub previous_label: String,
+    pub previous_tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedRow {
+    pub line: usize,
+    pub network: String,
+    pub address: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvalidRow {
+    pub line: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportCounts {
+    pub would_add: usize,
+    pub would_update_label: usize,
+    pub would_skip_duplicate: usize,
+    pub invalid: usize,
+}
+
+/// 导入与现有 address book 的差异
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportPlan {
+    pub would_add: Vec<PlannedAdd>,
+    pub would_update_label: Vec<PlannedUpdate>,
+    pub would_skip_duplicate: Vec<SkippedRow>,
+    pub invalid: Vec<InvalidRow>,
+}
+
+impl ImportPlan {
+    pub fn counts(&self) -> ImportCounts {
+        ImportCounts {
+            would_add: self.would_add.len(),
+            would_update_label: self.would_update_label.len(),
+            would_skip_duplicate: self.would_skip_duplicate.len(),
+            invalid: self.invalid.len(),
+        }
+    }
+
+    /// 提交时要写入的行：新增加上更新
+    pub fn changes(&self) -> Vec<AddressBookEntry> {
+        self.would_add
+            .iter()
+            .map(|add| add.entry.clone())
+            .chain(self.would_update_label.iter().map(|update| update.entry.clone()))
+            .collect()
+    }
+
+    pub fn is_noop(&self) -> bool {
+        self.would_add.is_empty() && self.would_update_label.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Columns {
+    label: usize,
+    address: usize,
+    network: usize,
+    tags: Option<usize>,
+    width: usize,
+}
+
+impl Columns {
+    fn from_header(record: &CsvRecord) -> Result<Self, CsvError> {
+        let mut found: HashMap<&str, usize> = HashMap::new();
+        for (i, name) in record.fields.iter().enumerate() {
+            let name = name.trim();
+            if !COLUMNS.contains(&name) {
+                return Err(CsvError::new(record.line, format!("unknown column '{}'", name)));
+            }
+            if found.insert(name, i).is_some() {
+                return Err(CsvError::new(record.line, format!("duplicate column '{}'", name)));
+            }
+        }
+        let required = |name: &str| {
+            found.get(name).copied().ok_or_else(|| CsvError::new(record.line, format!("missing column '{}'", name)))
+        };
+        Ok(Self {
+            label: required("label")?,
+            address: required("address")?,
+            network: required("network")?,
+            tags: found.get("tags").copied(),
+            width: record.fields.len(),
+        })
+    }
+}
+
+/// 逐块喂入 CSV，结束时得到 [`ImportPlan`]；不缓存原始文件
+pub struct AddressBookImport {
+    reader: CsvReader,
+    columns: Option<Columns>,
+    existing: HashMap<(String, String), AddressBookEntry>,
+    /// 文件内已出现的 address → 首次出现的行号
+    seen: HashMap<(String, String), usize>,
+    rows: usize,
+    plan: ImportPlan,
+}
+
+impl AddressBookImport {
+    pub fn new(existing: Vec<AddressBookEntry>) -> Self {
+        Self {
+            reader: CsvReader::new(),
+            columns: None,
+            existing: existing.into_iter().map(|entry| (entry.key(), entry)).collect(),
+            seen: HashMap::new(),
+            rows: 0,
+            plan: ImportPlan::default(),
+        }
+    }
+
+    /// CSV 本身格式错误（引号、编码、表头、行数超限）时返回错误；单行内容
+    /// 不合法只记入 `invalid`
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), CsvError> {
+        for record in self.reader.feed(chunk)? {
+            self.record(record)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<ImportPlan, CsvError> {
+        let reader = std::mem::take(&mut self.reader);
+        if let Some(record) = reader.finish()? {
+            self.record(record)?;
+        }
+        if self.columns.is_none() {
+            return Err(CsvError::new(1, "missing header row"));
+        }
+        Ok(self.plan)
+    }
+
+    fn record(&mut self, record: CsvRecord) -> Result<(), CsvError> {
+        let Some(columns) = self.columns else {
+            self.columns = Some(Columns::from_header(&record)?);
+            return Ok(());
+        };
+        self.rows += 1;
+        if self.rows > MAX_IMPORT_ROWS {
+            return Err(CsvError::new(record.line, format!("more than {} rows", MAX_IMPORT_ROWS)));
+        }
+        if record.fields.len() != columns.width {
+            return Err(CsvError::new(
+                record.line,
+                format!("expected {} fields, found {}", columns.width, record.fields.len()),
+            ));
+        }
+        let line = record.line;
+        let entry = match parse_row(&columns, &record.fields) {
+            Ok(entry) => entry,
+            Err(reason) => {
+                self.plan.invalid.push(InvalidRow { line, reason });
+                return Ok(());
+            }
+        };
+        let key = entry.key();
+        if let Some(first) = self.seen.get(&key) {
+            self.plan.would_skip_duplicate.push(SkippedRow {
+                line,
+                network: entry.network,
+                address: entry.address,
+                reason: format!("repeats line {}", first),
+            });
+            return Ok(());
+        }
+        self.seen.insert(key.clone(), line);
+        match self.existing.get(&key) {
+            None => self.plan.would_add.push(PlannedAdd { line, entry }),
+            Some(current) if current.label == entry.label && current.tags == entry.tags => {
+                self.plan.would_skip_duplicate.push(SkippedRow {
+                    line,
+                    network: entry.network,
+                    address: entry.address,
+                    reason: "already in address book".to_string(),
+                });
+            }
+            Some(current) => self.plan.would_update_label.push(PlannedUpdate {
+                line,
+                previous_label: current.label.clone(),
+                previous_tags: current.tags.clone(),
+                entry,
+            }),
+        }
+        Ok(())
+    }
+}
+
+fn parse_row(columns: &Columns, fields: &[String]) -> Result<AddressBookEntry, String> {
+    let network = NetworkName::try_from(fields[columns.network].trim()).map_err(|e| e.to_string())?;
+    let address = normalize_address(fields[columns.address].trim(), network)?;
+    let label = validate_label(fields[columns.label].trim())?;
+    let tags = parse_tags(columns.tags.map_or("", |i| fields[i].as_str()))?;
+    Ok(AddressBookEntry { network: network.as_str().to_string(), address, label, tags })
+}
+
+fn normalize_address(address: &str, network: NetworkName) -> Result<String, String> {
+    let matches = detect_address(address);
+    let Some(found) = matches.iter().find(|m| m.networks.iter().any(|n| *n == network.as_str())) else {
+        return Err(match matches.first() {
+            Some(other) => format!("{} is {}, not usable on {}", address, other.describe(), network.as_str()),
+            None => format!("'{}' is not a recognized address", address),
+        });
+    };
+    if found.checksum == Some("invalid") {
+        return Err(format!("EIP-55 checksum mismatch; expected {}", found.normalized));
+    }
+    Ok(found.normalized.clone())
+}
+
+/// 以 `=`、`+`、`-`、`@` 开头的文本在表格软件里会被当作公式执行，导出时
+/// 无法安全还原，导入时直接拒绝
+fn validate_label(label: &str) -> Result<String, String> {
+    if label.is_empty() {
+        return Err("label is empty".to_string());
+    }
+    if label.chars().count() > MAX_LABEL_LEN {
+        return Err(format!("label is longer than {} characters", MAX_LABEL_LEN));
+    }
+    if label.chars().any(char::is_control) {
+        return Err("label contains control characters".to_string());
+    }
+    if label.starts_with(['=', '+', '-', '@']) {
+        return Err("label must not start with '=', '+', '-' or '@'".to_string());
+    }
+    Ok(label.to_string())
+}
+
+fn parse_tags(tags: &str) -> Result<Vec<String>, String> {
+    let mut tags: Vec<String> =
+        tags.split(';').map(str::trim).filter(|tag| !tag.is_empty()).map(str::to_string).collect();
+    tags.sort();
+    tags.dedup();
+    if tags.len() > MAX_TAGS {
+        return Err(format!("more than {} tags", MAX_TAGS));
+    }
+    for tag in &tags {
+        validate_label(tag).map_err(|reason| reason.replacen("label", "tag", 1))?;
+    }
+    Ok(tags)
+}
+
+/// 规范 CSV：固定表头，按 (network, address) 排序
+pub fn export_csv(entries: &[AddressBookEntry]) -> String {
+    let sorted: BTreeMap<(&str, &str), &AddressBookEntry> =
+        entries.iter().map(|entry| ((entry.network.as_str(), entry.address.as_str()), entry)).collect();
+    let mut out = String::new();
+    write_record(&mut out, &COLUMNS);
+    for entry in sorted.values() {
+        let tags = entry.tags.join(";");
+        write_record(&mut out, &[entry.label.as_str(), entry.address.as_str(), entry.network.as_str(), tags.as_str()]);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(existing: Vec<AddressBookEntry>, csv: &str) -> Result<ImportPlan, CsvError> {
+        let mut import = AddressBookImport::new(existing);
+        import.feed(csv.as_bytes())?;
+        import.finish()
+    }
+
+    #[test]
+    fn test_rows_are_normalized() {
+        let csv = "network,address,tags,label\n\
+                   ethereum,0x52908400098527886e0f7030069857d2e4169ee7, b ;a;a ,Treasury\n";
+        let plan = plan(Vec::new(), csv).unwrap();
+        assert_eq!(
+            plan.would_add[0].entry,
+            AddressBookEntry {
+                network: "eth".to_string(),
+                address: "0x52908400098527886E0F7030069857D2E4169EE7".to_string(),
+                label: "Treasury".to_string(),
+                tags: vec!["a".to_string(), "b".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_row_level_rejections() {
+        let csv = "label,address,network\n\
+                   bad sum,0x52908400098527886E0F7030069857D2E4169Ee7,eth\n\
+                   =SUM(A1),0x52908400098527886E0F7030069857D2E4169EE7,eth\n\
+                   ,0x52908400098527886E0F7030069857D2E4169EE7,eth\n\
+                   wrong chain,0x52908400098527886E0F7030069857D2E4169EE7,btc\n\
+                   no net,0x52908400098527886E0F7030069857D2E4169EE7,solana\n";
+        let plan = plan(Vec::new(), csv).unwrap();
+        let lines: Vec<usize> = plan.invalid.iter().map(|row| row.line).collect();
+        assert_eq!(lines, vec![2, 3, 4, 5, 6]);
+        assert!(plan.invalid[0].reason.contains("checksum"), "{}", plan.invalid[0].reason);
+        assert!(plan.invalid[3].reason.contains("not usable on btc"), "{}", plan.invalid[3].reason);
+        assert!(plan.would_add.is_empty());
+    }
+
+    #[test]
+    fn test_header_problems_are_malformed_csv() {
+        assert_eq!(plan(Vec::new(), "label,address\n").unwrap_err().message, "missing column 'network'");
+        assert_eq!(plan(Vec::new(), "label,address,network,memo\n").unwrap_err().message, "unknown column 'memo'");
+        assert_eq!(plan(Vec::new(), "").unwrap_err().message, "missing header row");
+        let err = plan(Vec::new(), "label,address,network\na,b\n").unwrap_err();
+        assert_eq!((err.line, err.message.as_str()), (2, "expected 3 fields, found 2"));
+    }
+}
@@ -0,0 +1,288 @@
+//! 精确金额
+//!
+//! [`Amount`] 以最小单位（wei、sat、代币最小单位）的 `u128` 保存数值，并带上
+//! 小数位数与资产标记（network + 代币）。加减与按比例缩放都是 checked 运算，
+//! 不同资产之间不能相加或比较。
+//!
+//! 金额一律不经过 `f64`：用户输入用 [`Amount::parse`] 按整数解析，展示用
+//! [`Amount::to_decimal_string`]。唯一的例外是 [`Amount::to_f64_lossy`]，只供
+//! Prometheus 直方图这类本来就是近似值的观测使用。
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use ethers::types::U256;
+
+use crate::cli::amounts::Denomination;
+
+/// 金额最多支持的小数位（ERC-20 的 decimals 实际不超过 18，留出余量）
+pub const MAX_DECIMALS: u32 = 36;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AmountError {
+    #[error("Invalid amount {0:?}: expected digits with an optional fractional part")]
+    Format(String),
+    #[error("At most {0} decimal places are supported")]
+    TooPrecise(u32),
+    #[error("Amount overflows")]
+    Overflow,
+    #[error("Amount would be negative")]
+    Underflow,
+    #[error("Cannot combine {0} with {1}")]
+    AssetMismatch(String, String),
+    #[error("Unknown native asset for network {0}")]
+    UnknownNetwork(String),
+    #[error("Division by zero")]
+    DivisionByZero,
+}
+
+/// 资产标记：network 加代币（原生币用符号，ERC-20 用符号或合约address）
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssetTag {
+    pub network: String,
+    pub token: String,
+}
+
+impl AssetTag {
+    pub fn new(network: impl Into<String>, token: impl Into<String>) -> Self {
+        Self { network: network.into(), token: token.into() }
+    }
+}
+
+impl fmt::Display for AssetTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on {}", self.token, self.network)
+    }
+}
+
+/// 最小单位整数 + 小数位 + 资产
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Amount {
+    minimal: u128,
+    decimals: u32,
+    asset: AssetTag,
+}
+
+impl Amount {
+    pub fn new(minimal: u128, decimals: u32, asset: AssetTag) -> Self {
+        Self { minimal, decimals: decimals.min(MAX_DECIMALS), asset }
+    }
+
+    pub fn zero(decimals: u32, asset: AssetTag) -> Self {
+        Self::new(0, decimals, asset)
+    }
+
+    /// 链原生币的金额，如 eth 上的 wei
+    pub fn native(network: &str, minimal: u128) -> Result<Self, AmountError> {
+        let denom = Denomination::for_network(network).ok_or_else(|| AmountError::UnknownNetwork(network.to_string()))?;
+        Ok(Self::new(minimal, denom.decimals, AssetTag::new(network, denom.symbol)))
+    }
+
+    /// `U256` 最小单位（EVM 的 value / fee）；超过 `u128` 报 [`AmountError::Overflow`]
+    pub fn native_u256(network: &str, minimal: U256) -> Result<Self, AmountError> {
+        if minimal > U256::from(u128::MAX) {
+            return Err(AmountError::Overflow);
+        }
+        Self::native(network, minimal.as_u128())
+    }
+
+    /// 主单位十进制串 → 金额，如 `"1.5"`（18 位小数时为 1.5e18 最小单位）
+    ///
+    /// 只接受数字与一个小数点：无符号、指数、空白或千分位。允许 0，需要正数
+    /// 的调用方自行check [`is_zero`](Self::is_zero)。
+    pub fn parse(value: &str, decimals: u32, asset: AssetTag) -> Result<Self, AmountError> {
+        let decimals = decimals.min(MAX_DECIMALS);
+        let (int, frac) = value.split_once('.').unwrap_or((value, ""));
+        let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if int.is_empty() || !digits(int) || !digits(frac) || (value.contains('.') && frac.is_empty()) {
+            return Err(AmountError::Format(value.to_string()));
+        }
+        let frac = frac.trim_end_matches('0');
+        if frac.len() > decimals as usize {
+            return Err(AmountError::TooPrecise(decimals));
+        }
+        let scale = 10u128.checked_pow(decimals).ok_or(AmountError::Overflow)?;
+        let int: u128 = int.trim_start_matches('0').parse().or_else(|_| {
+            if int.bytes().all(|b| b == b'0') {
+                Ok(0)
+            } else {
+                Err(AmountError::Overflow)
+            }
+        })?;
+        let frac: u128 = if frac.is_empty() {
+            0
+        } else {
+            format!("{:0<width$}", frac, width = decimals as usize).parse().map_err(|_| AmountError::Overflow)?
+        };
+        let minimal = int.checked_mul(scale).and_then(|v| v.checked_add(frac)).ok_or(AmountError::Overflow)?;
+        Ok(Self { minimal, decimals, asset })
+    }
+
+    /// 原生币主单位十进制串，如 eth 上的 `"0.25"`
+    pub fn parse_native(network: &str, value: &str) -> Result<Self, AmountError> {
+        let denom = Denomination::for_network(network).ok_or_else(|| AmountError::UnknownNetwork(network.to_string()))?;
+        Self::parse(value, denom.decimals, AssetTag::new(network, denom.symbol))
+    }
+
+    pub fn minimal(&self) -> u128 {
+        self.minimal
+    }
+
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    pub fn asset(&self) -> &AssetTag {
+        &self.asset
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.minimal == 0
+    }
+
+    pub fn to_u256(&self) -> U256 {
+        U256::from(self.minimal)
+    }
+
+    fn same_asset(&self, other: &Self) -> Result<(), AmountError> {
+        if self.asset != other.asset || self.decimals != other.decimals {
+            return Err(AmountError::AssetMismatch(self.asset.to_string(), other.asset.to_string()));
+        }
+        Ok(())
+    }
+
+    fn with_minimal(&self, minimal: u128) -> Self {
+        Self { minimal, decimals: self.decimals, asset: self.asset.clone() }
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Result<Self, AmountError> {
+        self.same_asset(other)?;
+        self.minimal.checked_add(other.minimal).map(|m| self.with_minimal(m)).ok_or(AmountError::Overflow)
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Result<Self, AmountError> {
+        self.same_asset(other)?;
+        self.minimal.checked_sub(other.minimal).map(|m| self.with_minimal(m)).ok_or(AmountError::Underflow)
+    }
+
+    pub fn checked_mul(&self, factor: u128) -> Result<Self, AmountError> {
+        self.minimal.checked_mul(factor).map(|m| self.with_minimal(m)).ok_or(AmountError::Overflow)
+    }
+
+    /// `self * numerator / denominator`，向下取整；中间结果用 U256 计算，
+    /// 所以只有最终结果超出 `u128` 才报溢出（如按基点算手续费）。
+    pub fn mul_div_floor(&self, numerator: u128, denominator: u128) -> Result<Self, AmountError> {
+        if denominator == 0 {
+            return Err(AmountError::DivisionByZero);
+        }
+        let scaled = U256::from(self.minimal) * U256::from(numerator) / U256::from(denominator);
+        if scaled > U256::from(u128::MAX) {
+            return Err(AmountError::Overflow);
+        }
+        Ok(self.with_minimal(scaled.as_u128()))
+    }
+
+    /// 主单位的精确十进制串（无分组，去掉末尾的 0），如 `1.5`
+    pub fn to_decimal_string(&self) -> String {
+        if self.decimals == 0 {
+            return self.minimal.to_string();
+        }
+        let scale = 10u128.pow(self.decimals);
+        let (int, frac) = (self.minimal / scale, self.minimal % scale);
+        if frac == 0 {
+            return int.to_string();
+        }
+        let frac = format!("{:0>width$}", frac, width = self.decimals as usize);
+        format!("{}.{}", int, frac.trim_end_matches('0'))
+    }
+
+    /// 主单位的近似浮点值，**会丢精度**（超过 2^53 个最小单位后不再精确）。
+    /// 只用于直方图观测、统计特征等本就近似的场合，不可回写或参与金额运算。
+    pub fn to_f64_lossy(&self) -> f64 {
+        // 整数与小数部分分开换算，避免 u128 → f64 后再除以 10^decimals 放大误差
+        let scale = 10u128.pow(self.decimals);
+        let (int, frac) = (self.minimal / scale, self.minimal % scale);
+        int as f64 + frac as f64 / scale as f64
+    }
+}
+
+/// 只有同一资产、同一小数位的金额可比较
+impl PartialOrd for Amount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.same_asset(other).ok()?;
+        Some(self.minimal.cmp(&other.minimal))
+    }
+}
+
+/// `1.5 ETH`
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.to_decimal_string(), self.asset.token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eth(minimal: u128) -> Amount {
+        Amount::native("eth", minimal).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        let a = Amount::parse_native("eth", "1.5").unwrap();
+        assert_eq!(a.minimal(), 1_500_000_000_000_000_000);
+        assert_eq!(a.to_string(), "1.5 ETH");
+        assert_eq!(Amount::parse_native("btc", "0.00000001").unwrap().minimal(), 1);
+        assert_eq!(Amount::parse_native("eth", "0").unwrap().to_decimal_string(), "0");
+        assert_eq!(Amount::parse_native("eth", "007.10").unwrap().to_decimal_string(), "7.1");
+
+        for bad in ["", ".5", "1.", "-1", "1e18", "1,5", " 1", "0x10", "1.2.3"] {
+            assert!(matches!(Amount::parse_native("eth", bad), Err(AmountError::Format(_))), "{:?}", bad);
+        }
+        assert_eq!(Amount::parse_native("btc", "0.000000001"), Err(AmountError::TooPrecise(8)));
+        assert_eq!(Amount::parse_native("doge", "1"), Err(AmountError::UnknownNetwork("doge".to_string())));
+    }
+
+    #[test]
+    fn test_values_above_f64_precision_stay_exact() {
+        // 2^53 + 1 wei：f64 会舍入到 2^53
+        let v = Amount::parse_native("eth", "0.009007199254740993").unwrap();
+        assert_eq!(v.minimal(), 9_007_199_254_740_993);
+        assert_eq!(v.to_decimal_string(), "0.009007199254740993");
+        assert_ne!((v.to_f64_lossy() * 1e18) as u128, v.minimal());
+    }
+
+    #[test]
+    fn test_u128_boundaries() {
+        let max = Amount::new(u128::MAX, 0, AssetTag::new("eth", "T"));
+        assert_eq!(max.to_decimal_string(), u128::MAX.to_string());
+        assert_eq!(Amount::parse(&u128::MAX.to_string(), 0, max.asset().clone()).unwrap(), max);
+        let over = format!("{}0", u128::MAX);
+        assert_eq!(Amount::parse(&over, 0, max.asset().clone()), Err(AmountError::Overflow));
+        // 整数部分放得下，乘上 10^18 后溢出
+        assert_eq!(Amount::parse_native("eth", "340282366920938463464").unwrap_err(), AmountError::Overflow);
+
+        let one = Amount::new(1, 0, max.asset().clone());
+        assert_eq!(max.checked_add(&one), Err(AmountError::Overflow));
+        assert_eq!(max.checked_sub(&max).unwrap().minimal(), 0);
+        assert_eq!(Amount::zero(0, max.asset().clone()).checked_sub(&one), Err(AmountError::Underflow));
+        assert_eq!(max.checked_mul(2), Err(AmountError::Overflow));
+        // 中间结果超出 u128 也能按比例缩放
+        assert_eq!(max.mul_div_floor(3, 4).unwrap().minimal(), u128::MAX / 4 * 3 + 2);
+        assert_eq!(max.mul_div_floor(2, 1), Err(AmountError::Overflow));
+        assert_eq!(max.mul_div_floor(1, 0), Err(AmountError::DivisionByZero));
+        assert_eq!(Amount::native_u256("eth", U256::from(u128::MAX) + 1), Err(AmountError::Overflow));
+    }
+
+    #[test]
+    fn test_mixed_assets_do_not_combine() {
+        let wei = eth(10);
+        let sat = Amount::native("btc", 10).unwrap();
+        assert!(matches!(wei.checked_add(&sat), Err(AmountError::AssetMismatch(_, _))));
+        assert_eq!(wei.partial_cmp(&sat), None);
+        assert!(eth(9) < wei);
+        assert_eq!(eth(30).mul_div_floor(1, 4).unwrap(), eth(7));
+    }
+}
@@ -51,6 +51,8 @@ fn create_mock_wallet_data() -> SecureWalletData {
             quantum_safe: true,
             multi_sig_threshold: 1,
             networks: vec!["eth".to_string(), "polygon".to_string(), "bsc".to_string()],
+            allowed_networks: None,
+            derivation_path: None,
         },
         encrypted_master_key: vec![1, 2, 3, 4],
         shamir_shares: vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]],
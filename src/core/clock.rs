@@ -0,0 +1,79 @@
+//! Injectable wall clock
+//!
+//! Storage and the wallet manager read the current time through [`Clock`] so
+//! tests can pin it. Production code uses [`SystemClock`]; [`FixedClock`]
+//! only moves when told to.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use parking_lot::Mutex;
+use std::fmt;
+use std::sync::Arc;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+impl fmt::Debug for dyn Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Clock")
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Shared handle to the system clock
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// A clock that stands still until [`FixedClock::advance`] or [`FixedClock::set`].
+#[derive(Debug)]
+pub struct FixedClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(now) }
+    }
+
+    /// Fixed at Unix second `secs`
+    pub fn at(secs: i64) -> Self {
+        Self::new(Utc.timestamp_opt(secs, 0).single().expect("timestamp out of range"))
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock() += by;
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock() = now;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock_only_moves_when_told() {
+        let clock = FixedClock::at(1_700_000_000);
+        assert_eq!(clock.now().timestamp(), 1_700_000_000);
+        assert_eq!(clock.now(), clock.now());
+        clock.advance(Duration::seconds(90));
+        assert_eq!(clock.now().timestamp(), 1_700_000_090);
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use crate::core::errors::WalletError;
+use crate::blockchain::erc20::TokenBehavior;
+use crate::anomaly_detection::AnomalyDetectionConfig;
 
 /// Security configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +30,246 @@ pub struct SecurityConfig {
     /// Rate limiter maximum entries
     #[serde(default = "SecurityConfig::default_rate_limiter_max_entries")]
     pub rate_limiter_max_entries: usize,
+
+    /// Signing key rotation policy
+    #[serde(default)]
+    pub key_rotation: KeyRotationPolicy,
+
+    /// scrypt cost (N) for exported keystore V3 files
+    #[serde(default = "SecurityConfig::default_keystore_scrypt_n")]
+    pub keystore_scrypt_n: u32,
+
+    /// argon2id memory cost (KiB) for exported wallet keystores
+    #[serde(default = "SecurityConfig::default_wallet_keystore_m_cost")]
+    pub wallet_keystore_m_cost: u32,
+
+    /// Window (seconds) in which an identical pending send is refused as a
+    /// suspected duplicate; 0 disables the check
+    #[serde(default = "SecurityConfig::default_duplicate_send_window_secs")]
+    pub duplicate_send_window_secs: u64,
+
+    /// Dead-man's switch evaluator
+    #[serde(default)]
+    pub deadman: DeadmanConfig,
+
+    /// Encoder for signed Ethereum transactions
+    #[serde(default)]
+    pub tx_encoding: TxEncoding,
+
+    /// Where the master key and API key come from
+    #[serde(default)]
+    pub secret_backend: SecretBackendConfig,
+
+    /// Secrets written inline in the config file
+    #[serde(default)]
+    pub config_secrets: ConfigSecretsConfig,
+}
+
+/// Which encoder produces signed Ethereum transactions. Both yield the same
+/// bytes; `native` uses [`crate::core::rlp`] instead of ethers.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TxEncoding {
+    #[default]
+    Ethers,
+    Native,
+}
+
+/// Source of `WALLET_ENC_KEY` and `API_KEY`, re-read periodically so that a
+/// key rotated or revoked in the backend takes effect without a restart.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SecretBackendConfig {
+    pub source: SecretSourceConfig,
+    /// Re-read interval (seconds); 0 reads the backend only at startup
+    pub refresh_interval_secs: u64,
+    /// How long a cached key stays usable after the backend last confirmed
+    /// it (seconds); 0 keeps it usable indefinitely
+    pub max_staleness_secs: u64,
+    pub on_rotation: MasterKeyRotation,
+}
+
+impl Default for SecretBackendConfig {
+    fn default() -> Self {
+        Self {
+            source: SecretSourceConfig::Env,
+            refresh_interval_secs: 300,
+            max_staleness_secs: 900,
+            on_rotation: MasterKeyRotation::default(),
+        }
+    }
+}
+
+/// Backend holding the secrets. Credentials for the backend itself are read
+/// from environment variables named here, never from the config file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SecretSourceConfig {
+    /// Process environment (the historical behaviour)
+    #[default]
+    Env,
+    /// One file per secret, named after it (e.g. `/run/secrets/WALLET_ENC_KEY`)
+    File { directory: std::path::PathBuf },
+    /// OS keychain; needs the `os-keychain` feature
+    Keychain {
+        #[serde(default = "default_keychain_service")]
+        service: String,
+        /// Secret name -> keychain entry; unlisted secrets use their own name
+        #[serde(default)]
+        entries: HashMap<String, String>,
+    },
+    /// HashiCorp Vault KV v2
+    Vault(VaultSecretConfig),
+}
+
+/// `[security.config_secrets]`. Any string in config.toml may be a
+/// `${secure:NAME}` reference resolved through the secret backend at load;
+/// this decides what happens to secrets still written inline.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct ConfigSecretsConfig {
+    pub inline: InlineSecretPolicy,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InlineSecretPolicy {
+    /// Log each config path with the suggested reference and start anyway
+    #[default]
+    Warn,
+    /// Refuse to start
+    Strict,
+}
+
+fn default_keychain_service() -> String {
+    "defi-hot-wallet".to_string()
+}
+
+/// One KV v2 secret holding every key as a field.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VaultSecretConfig {
+    /// e.g. `https://vault.internal:8200`
+    pub address: String,
+    /// KV v2 mount
+    #[serde(default = "default_vault_mount")]
+    pub mount: String,
+    /// Secret path below the mount
+    pub path: String,
+    /// Enterprise namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub auth: VaultAuthConfig,
+    /// Secret name -> field; unlisted secrets use their lowercase name
+    #[serde(default)]
+    pub fields: HashMap<String, String>,
+    /// PEM bundle trusted in addition to the system roots
+    #[serde(default)]
+    pub ca_cert: Option<std::path::PathBuf>,
+    #[serde(default = "default_vault_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_vault_mount() -> String {
+    "secret".to_string()
+}
+
+fn default_vault_timeout_secs() -> u64 {
+    10
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "method", rename_all = "lowercase")]
+pub enum VaultAuthConfig {
+    Token {
+        #[serde(default = "default_vault_token_env")]
+        token_env: String,
+    },
+    AppRole {
+        #[serde(default = "default_vault_approle_mount")]
+        mount: String,
+        #[serde(default = "default_vault_role_id_env")]
+        role_id_env: String,
+        #[serde(default = "default_vault_secret_id_env")]
+        secret_id_env: String,
+    },
+}
+
+impl Default for VaultAuthConfig {
+    fn default() -> Self {
+        VaultAuthConfig::Token { token_env: default_vault_token_env() }
+    }
+}
+
+fn default_vault_token_env() -> String {
+    "VAULT_TOKEN".to_string()
+}
+
+fn default_vault_approle_mount() -> String {
+    "approle".to_string()
+}
+
+fn default_vault_role_id_env() -> String {
+    "VAULT_ROLE_ID".to_string()
+}
+
+fn default_vault_secret_id_env() -> String {
+    "VAULT_SECRET_ID".to_string()
+}
+
+/// What the refresh does when the backend returns a different master key.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MasterKeyRotation {
+    /// Keep the wallets as they are and refuse to use the master key until an
+    /// operator runs `rotate-kek` and restarts.
+    #[default]
+    Refuse,
+    /// Re-wrap every wallet envelope under the new key, then adopt it.
+    Rewrap,
+}
+
+/// Dead-man's switch: periodic evaluation of per-wallet inactivity policies.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct DeadmanConfig {
+    pub enabled: bool,
+    /// Evaluation interval (seconds)
+    pub interval_secs: u64,
+    /// Shortest inactivity period a policy may configure (seconds)
+    pub min_inactivity_secs: u64,
+}
+
+impl Default for DeadmanConfig {
+    fn default() -> Self {
+        Self { enabled: true, interval_secs: 300, min_inactivity_secs: 24 * 3600 }
+    }
+}
+
+/// What happens once a signing key is past its rotation policy.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyPolicyMode {
+    /// Keep signing, but record a metric and a security event.
+    #[default]
+    Warn,
+    /// Refuse to sign until the key is rotated (`KEY_ROTATION_REQUIRED`).
+    Block,
+}
+
+/// Per-key-version signing budget. Unset limits are not enforced.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct KeyRotationPolicy {
+    /// Maximum signatures produced by one key version
+    #[serde(default)]
+    pub max_signatures_per_key: Option<u64>,
+
+    /// Maximum key version age (seconds)
+    #[serde(default)]
+    pub max_key_age_secs: Option<u64>,
+
+    #[serde(default)]
+    pub policy_mode: KeyPolicyMode,
 }
 
 impl SecurityConfig {
@@ -36,6 +279,9 @@ impl SecurityConfig {
     fn default_max_sessions_per_user() -> usize { 5 }
     fn default_csrf_ttl() -> u64 { 3600 }
     fn default_rate_limiter_max_entries() -> usize { 10_000 }
+    fn default_keystore_scrypt_n() -> u32 { crate::crypto::keystore_v3::DEFAULT_SCRYPT_N }
+    fn default_wallet_keystore_m_cost() -> u32 { crate::crypto::wallet_keystore::DEFAULT_ARGON2_M_COST }
+    fn default_duplicate_send_window_secs() -> u64 { 120 }
 }
 
 impl Default for SecurityConfig {
@@ -47,6 +293,14 @@ impl Default for SecurityConfig {
             max_sessions_per_user: Self::default_max_sessions_per_user(),
             csrf_token_ttl: Self::default_csrf_ttl(),
             rate_limiter_max_entries: Self::default_rate_limiter_max_entries(),
+            key_rotation: KeyRotationPolicy::default(),
+            keystore_scrypt_n: Self::default_keystore_scrypt_n(),
+            wallet_keystore_m_cost: Self::default_wallet_keystore_m_cost(),
+            duplicate_send_window_secs: Self::default_duplicate_send_window_secs(),
+            deadman: DeadmanConfig::default(),
+            tx_encoding: TxEncoding::default(),
+            secret_backend: SecretBackendConfig::default(),
+            config_secrets: ConfigSecretsConfig::default(),
         }
     }
 }
@@ -57,6 +311,10 @@ pub struct StorageConfig {
     pub database_url: String,
     pub max_connections: Option<u32>,
     pub connection_timeout_seconds: Option<u64>,
+    /// Read replica for listings, history, audit and statistics (sqlite URL,
+    /// opened read-only). Unset: every query goes to `database_url`.
+    #[serde(default)]
+    pub read_database_url: Option<String>,
 }
 
 /// Blockchain network configuration
@@ -65,6 +323,147 @@ pub struct NetworkConfig {
     pub name: String,
     pub rpc_url: String,
     pub chain_id: u64,
+    /// 区块浏览器链接；未配置时响应中不输出 `explorer_url`。
+    /// 旧键 `explorer_url`（根地址字符串）仍被接受
+    #[serde(default, alias = "explorer_url", skip_serializing_if = "Option::is_none")]
+    pub explorer_url_template: Option<ExplorerUrlTemplate>,
+    /// `rpc_url` 的出站配额；未配置时不限流
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RpcRateLimit>,
+    /// 主节点配额用尽时按顺序改用的备用节点
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_endpoints: Vec<RpcEndpointConfig>,
+    /// 发送后超过此秒数仍 pending 且已不在 mempool 的transaction标记为 `expired`；未配置时不过期
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_expiry_seconds: Option<u64>,
+}
+
+impl NetworkConfig {
+    /// 视为最终确认所需的区块数（按 chain id 的经验值）
+    pub fn required_confirmations(&self) -> u64 {
+        match self.chain_id {
+            1 => 12,
+            56 => 15,
+            137 => 128,
+            _ => 3,
+        }
+    }
+
+    /// 是否为主网（真实资产）；sandbox 数据库拒绝加载这些网络
+    pub fn is_mainnet(&self) -> bool {
+        MAINNET_CHAIN_IDS.contains(&self.chain_id)
+    }
+}
+
+/// 已知主网 chain id：Ethereum、Optimism、BSC、Gnosis、Polygon、Fantom、zkSync Era、
+/// Base、Arbitrum One、Avalanche C-Chain、Linea
+pub const MAINNET_CHAIN_IDS: &[u64] = &[1, 10, 56, 100, 137, 250, 324, 8453, 42161, 43114, 59144];
+
+/// 出站 RPC 令牌桶：每秒一个桶（容量 `burst`，缺省等于每秒请求数），
+/// 配置了 `requests_per_day` 时再加一个按天回填的桶
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcRateLimit {
+    pub requests_per_second: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_day: Option<u64>,
+}
+
+impl RpcRateLimit {
+    fn validate(&self) -> Result<(), String> {
+        if self.requests_per_second == 0 || self.burst == Some(0) || self.requests_per_day == Some(0) {
+            return Err("requests_per_second, burst and requests_per_day must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 备用 RPC 节点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcEndpointConfig {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RpcRateLimit>,
+}
+
+/// 区块浏览器链接模板：`tx` 必须含 `{hash}`，`address` 必须含 `{address}`，
+/// 不允许其他占位符
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "ExplorerUrlSetting")]
+pub struct ExplorerUrlTemplate {
+    pub tx: String,
+    pub address: String,
+}
+
+/// 模板表，或旧配置 `explorer_url = "<base>"` 中的浏览器根地址（按 Etherscan 风格展开）
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExplorerUrlSetting {
+    Template { tx: String, address: String },
+    Base(String),
+}
+
+impl From<ExplorerUrlSetting> for ExplorerUrlTemplate {
+    fn from(setting: ExplorerUrlSetting) -> Self {
+        match setting {
+            ExplorerUrlSetting::Template { tx, address } => Self { tx, address },
+            ExplorerUrlSetting::Base(base) => Self::etherscan_style(&base),
+        }
+    }
+}
+
+impl ExplorerUrlTemplate {
+    /// `<base>/tx/{hash}` 与 `<base>/address/{address}`（Etherscan 系浏览器通用）
+    pub fn etherscan_style(base: &str) -> Self {
+        let base = base.trim_end_matches('/');
+        Self { tx: format!("{}/tx/{{hash}}", base), address: format!("{}/address/{{address}}", base) }
+    }
+
+    pub fn tx_url(&self, hash: &str) -> String {
+        self.tx.replace("{hash}", hash)
+    }
+
+    pub fn address_url(&self, address: &str) -> String {
+        self.address.replace("{address}", address)
+    }
+
+    /// scheme + host of the transaction template, e.g. `https://etherscan.io`
+    pub fn base(&self) -> &str {
+        let host_start = self.tx.find("://").map_or(0, |i| i + 3);
+        match self.tx[host_start..].find('/') {
+            Some(end) => &self.tx[..host_start + end],
+            None => &self.tx,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        check_template("tx", &self.tx, "hash")?;
+        check_template("address", &self.address, "address")
+    }
+}
+
+fn check_template(field: &str, template: &str, placeholder: &str) -> Result<(), String> {
+    if !(template.starts_with("https://") || template.starts_with("http://")) {
+        return Err(format!("{} template must be an http(s) URL: {}", field, template));
+    }
+    let mut found = false;
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| format!("{} template has an unclosed placeholder: {}", field, template))?;
+        let name = &rest[open + 1..open + close];
+        if name != placeholder {
+            return Err(format!("{} template has unknown placeholder {{{}}}: {}", field, name, template));
+        }
+        found = true;
+        rest = &rest[open + close + 1..];
+    }
+    if !found {
+        return Err(format!("{} template must contain {{{}}}: {}", field, placeholder, template));
+    }
+    Ok(())
 }
 
 /// Blockchain configuration
@@ -81,18 +480,92 @@ impl Default for BlockchainConfig {
             name: "Ethereum Mainnet".to_string(),
             rpc_url: "https://eth.llamarpc.com".to_string(),
             chain_id: 1,
+            explorer_url_template: Some(ExplorerUrlTemplate::etherscan_style("https://etherscan.io")),
+            rate_limit: None,
+            fallback_endpoints: Vec::new(),
+            pending_expiry_seconds: None,
         });
         
         networks.insert("sepolia".to_string(), NetworkConfig {
             name: "Sepolia Testnet".to_string(),
             rpc_url: "https://rpc.sepolia.org".to_string(),
             chain_id: 11155111,
+            explorer_url_template: Some(ExplorerUrlTemplate::etherscan_style("https://sepolia.etherscan.io")),
+            rate_limit: None,
+            fallback_endpoints: Vec::new(),
+            pending_expiry_seconds: None,
         });
         
         Self { networks }
     }
 }
 
+impl BlockchainConfig {
+    /// 启动时校验：浏览器模板缺少占位符或含未知占位符、限流参数或过期时长为 0 时拒绝启动
+    pub fn validate(&self) -> Result<(), WalletError> {
+        for (name, network) in &self.networks {
+            if let Some(template) = &network.explorer_url_template {
+                template.validate().map_err(|e| {
+                    WalletError::ConfigError(format!("blockchain.networks.{}.explorer_url_template: {}", name, e))
+                })?;
+            }
+            let limits = std::iter::once(("rate_limit".to_string(), &network.rate_limit)).chain(
+                network
+                    .fallback_endpoints
+                    .iter()
+                    .enumerate()
+                    .map(|(i, endpoint)| (format!("fallback_endpoints[{}].rate_limit", i), &endpoint.rate_limit)),
+            );
+            for (field, limit) in limits {
+                if let Some(limit) = limit {
+                    limit.validate().map_err(|e| {
+                        WalletError::ConfigError(format!("blockchain.networks.{}.{}: {}", name, field, e))
+                    })?;
+                }
+            }
+            if network.pending_expiry_seconds == Some(0) {
+                return Err(WalletError::ConfigError(format!(
+                    "blockchain.networks.{}.pending_expiry_seconds must be positive",
+                    name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// 移除所有主网网络，返回被移除的网络名（排序后）
+    pub fn remove_mainnets(&mut self) -> Vec<String> {
+        let mut removed: Vec<String> =
+            self.networks.iter().filter(|(_, network)| network.is_mainnet()).map(|(name, _)| name.clone()).collect();
+        removed.sort();
+        for name in &removed {
+            self.networks.remove(name);
+        }
+        removed
+    }
+
+    pub fn explorer_tx_url(&self, network: &str, hash: &str) -> Option<String> {
+        self.explorer(network).map(|t| t.tx_url(hash))
+    }
+
+    pub fn explorer_address_url(&self, network: &str, address: &str) -> Option<String> {
+        self.explorer(network).map(|t| t.address_url(address))
+    }
+
+    fn explorer(&self, network: &str) -> Option<&ExplorerUrlTemplate> {
+        self.networks.get(network)?.explorer_url_template.as_ref()
+    }
+
+    /// 配置了该 chain id 的network；多个network共用时取名字最小的，保证结果稳定
+    pub fn network_for_chain_id(&self, chain_id: u64) -> Option<&str> {
+        self.networks
+            .iter()
+            .filter(|(_, network)| network.chain_id == chain_id)
+            .map(|(name, _)| name.as_str())
+            .min()
+    }
+}
+
 /// Derivation path configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DerivationConfig {
@@ -107,6 +580,734 @@ impl Default for DerivationConfig {
     }
 }
 
+/// 跨链桥后端
+///
+/// `Mock` 只能在 `test-env` 构建或显式传入 `--allow-insecure-mocks` 时启用，
+/// 由 [`BridgeBackend::resolve`] 在启动时统一判定。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BridgeBackend {
+    #[default]
+    Real,
+    Mock,
+}
+
+impl BridgeBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BridgeBackend::Real => "real",
+            BridgeBackend::Mock => "mock",
+        }
+    }
+
+    /// 判定最终生效的后端；生产构建未显式放行时拒绝 `Mock`。
+    pub fn resolve(self, allow_insecure_mocks: bool) -> Result<BridgeBackend, WalletError> {
+        match self {
+            BridgeBackend::Real => Ok(BridgeBackend::Real),
+            BridgeBackend::Mock if cfg!(feature = "test-env") || allow_insecure_mocks => {
+                Ok(BridgeBackend::Mock)
+            }
+            BridgeBackend::Mock => Err(WalletError::ConfigError(
+                "bridge_backend = \"mock\" requires a `test-env` build or --allow-insecure-mocks"
+                    .to_string(),
+            )),
+        }
+    }
+}
+
+impl std::str::FromStr for BridgeBackend {
+    type Err = WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "real" => Ok(BridgeBackend::Real),
+            "mock" => Ok(BridgeBackend::Mock),
+            other => Err(WalletError::ConfigError(format!("Unknown bridge backend: {}", other))),
+        }
+    }
+}
+
+/// 余额快照后台任务配置（余额历史图表）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceSnapshotConfig {
+    pub enabled: bool,
+    /// 快照间隔（秒），默认每小时
+    pub interval_secs: u64,
+    /// 每个network同时进行的balance查询数
+    pub per_network_concurrency: usize,
+    /// 保留小时粒度的天数，更早的快照降采样为每日一条
+    pub hourly_retention_days: u32,
+    /// 参与快照的network；为空表示所有已配置network
+    pub networks: Vec<String>,
+    /// 余额订阅的防抖窗口（秒）：窗口内的连续变化合并为一个 `balance.changed` 事件
+    pub subscription_debounce_secs: u64,
+}
+
+impl Default for BalanceSnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 3_600,
+            per_network_concurrency: 4,
+            hourly_retention_days: 7,
+            networks: Vec::new(),
+            subscription_debounce_secs: 60,
+        }
+    }
+}
+
+/// 大额transaction四眼审批
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApprovalConfig {
+    /// 拥有审批权限的user（user id 或邮箱）；为空时无人可审批
+    pub approvers: Vec<String>,
+    /// 待审批请求的有效期（秒）；过期后不可执行
+    pub expiry_secs: u64,
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self { approvers: Vec::new(), expiry_secs: 24 * 3600 }
+    }
+}
+
+/// 法币估值：余额与历史响应中的 `fiat_value`（CoinGecko 兼容价格源）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PricingConfig {
+    pub enabled: bool,
+    /// 价格 API 根地址，如 `https://api.coingecko.com/api/v3`
+    pub base_url: String,
+    /// API key 所在的环境变量，不写入配置文件；变量未设置时匿名访问
+    pub api_key_env: String,
+    /// 携带 API key 的请求头（Pro 版为 `x-cg-pro-api-key`）
+    pub api_key_header: String,
+    /// user未设置偏好时的计价货币；余额快照任务也按该货币记录历史价格
+    pub default_currency: String,
+    /// 价格新鲜期（秒）
+    pub cache_ttl_secs: u64,
+    /// 过期后仍直接返回旧价格、同时后台刷新的时长（秒）
+    pub stale_secs: u64,
+    pub request_timeout_secs: u64,
+    /// 符号 → CoinGecko coin id，补充或覆盖内置映射
+    pub coin_ids: std::collections::BTreeMap<String, String>,
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            base_url: "https://api.coingecko.com/api/v3".to_string(),
+            api_key_env: "PRICE_FEED_API_KEY".to_string(),
+            api_key_header: "x-cg-demo-api-key".to_string(),
+            default_currency: "usd".to_string(),
+            cache_ttl_secs: 60,
+            stale_secs: 600,
+            request_timeout_secs: 5,
+            coin_ids: std::collections::BTreeMap::new(),
+        }
+    }
+}
+
+/// 手续费历史：确认轮询与 gas 价格采样
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeeTrackingConfig {
+    pub enabled: bool,
+    /// 查询待确认transaction收据的间隔（秒）
+    pub confirmation_interval_secs: u64,
+    /// 超过该时长仍无收据的transaction不再轮询（小时）
+    pub confirmation_max_age_hours: u64,
+    /// gas oracle 采样间隔（秒）
+    pub gas_sample_interval_secs: u64,
+    /// 参与采样的network；为空表示 gas oracle 支持的所有已配置network
+    pub networks: Vec<String>,
+    /// 按区块跟踪确认的network；这些network不再逐笔轮询收据
+    pub block_tracking: BlockTrackingConfig,
+}
+
+impl Default for FeeTrackingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            confirmation_interval_secs: 30,
+            confirmation_max_age_hours: 24,
+            gas_sample_interval_secs: 300,
+            networks: Vec::new(),
+            block_tracking: BlockTrackingConfig::default(),
+        }
+    }
+}
+
+/// 区块驱动的确认跟踪：每个新区块只为其中属于我们的待确认transaction取收据，
+/// 确认数按高度差推进；RPC 地址为 ws:// 时通过 newHeads 订阅获知新区块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlockTrackingConfig {
+    /// 启用区块跟踪的network；为空表示全部沿用逐笔轮询
+    pub networks: Vec<String>,
+    /// 查询最新区块的间隔（秒）
+    pub poll_interval_secs: u64,
+    /// 每个network内存中最多跟踪的待确认transaction数
+    pub max_pending: usize,
+    /// 与数据库同步待确认集合的间隔（秒）
+    pub reconcile_interval_secs: u64,
+    /// 保留最近多少个区块的 hash 用于检测重组
+    pub reorg_window: u64,
+    /// 落后较多时每轮最多处理的区块数
+    pub max_blocks_per_cycle: u64,
+}
+
+impl Default for BlockTrackingConfig {
+    fn default() -> Self {
+        Self {
+            networks: Vec::new(),
+            poll_interval_secs: 4,
+            max_pending: 1_000,
+            reconcile_interval_secs: 30,
+            reorg_window: 64,
+            max_blocks_per_cycle: 50,
+        }
+    }
+}
+
+impl BlockTrackingConfig {
+    pub fn tracks(&self, network: &str) -> bool {
+        self.networks.iter().any(|n| n == network)
+    }
+}
+
+/// 数据库自动备份配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    pub enabled: bool,
+    /// 备份间隔（秒），默认每天
+    pub interval_secs: u64,
+    pub sink: BackupSinkConfig,
+    /// 始终保留最近的 N 份
+    pub keep_last: usize,
+    /// 另外保留最近 M 天中每天最后一份
+    pub keep_daily: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 86_400,
+            sink: BackupSinkConfig::default(),
+            keep_last: 7,
+            keep_daily: 30,
+        }
+    }
+}
+
+/// 备份写入目标；S3 凭据从环境变量读取，不写入配置文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BackupSinkConfig {
+    Filesystem {
+        directory: std::path::PathBuf,
+    },
+    /// S3 兼容存储（path-style 访问，如 MinIO）
+    S3 {
+        endpoint: String,
+        bucket: String,
+        #[serde(default = "default_s3_region")]
+        region: String,
+        #[serde(default)]
+        prefix: String,
+        #[serde(default = "default_s3_access_key_env")]
+        access_key_env: String,
+        #[serde(default = "default_s3_secret_key_env")]
+        secret_key_env: String,
+    },
+}
+
+impl Default for BackupSinkConfig {
+    fn default() -> Self {
+        BackupSinkConfig::Filesystem { directory: "./backups".into() }
+    }
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_s3_access_key_env() -> String {
+    "BACKUP_S3_ACCESS_KEY_ID".to_string()
+}
+
+fn default_s3_secret_key_env() -> String {
+    "BACKUP_S3_SECRET_ACCESS_KEY".to_string()
+}
+
+/// 启动前依赖检查（`[preflight]`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreflightConfig {
+    /// 每个探测（RPC、外部集成）的超时
+    pub probe_timeout_secs: u64,
+    /// 除数据库目录与备份目录外，额外需要可写的目录
+    pub writable_dirs: Vec<std::path::PathBuf>,
+    /// 可选外部集成（webhook、价格源、合规服务）：名称 → 健康检查 URL；
+    /// 不可达只产生警告
+    pub integrations: std::collections::BTreeMap<String, String>,
+}
+
+impl Default for PreflightConfig {
+    fn default() -> Self {
+        Self {
+            probe_timeout_secs: 5,
+            writable_dirs: Vec::new(),
+            integrations: std::collections::BTreeMap::new(),
+        }
+    }
+}
+
+/// EIP-2771 元transaction中继配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelayConfig {
+    pub enabled: bool,
+    /// 提交 `execute` 的network
+    pub network: String,
+    pub chain_id: u64,
+    /// 受信任的 forwarder 合约address
+    pub forwarder_address: String,
+    /// forwarder 的 EIP-712 domain name / version
+    pub forwarder_name: String,
+    pub forwarder_version: String,
+    /// 代付 gas 的热wallet
+    pub relayer_wallet: String,
+    /// relayer wallet 口令所在的环境变量，不写入配置文件
+    pub relayer_password_env: String,
+    /// 每个 from address 在一个窗口内最多中继的次数
+    pub quota_per_address: u32,
+    pub quota_window_secs: u64,
+    /// 单个请求允许的最大 gas
+    pub max_gas: u64,
+}
+
+impl Default for RelayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            network: "sepolia".to_string(),
+            chain_id: 11_155_111,
+            forwarder_address: String::new(),
+            forwarder_name: "MinimalForwarder".to_string(),
+            forwarder_version: "0.0.1".to_string(),
+            relayer_wallet: "relayer".to_string(),
+            relayer_password_env: "RELAYER_WALLET_PASSWORD".to_string(),
+            quota_per_address: 20,
+            quota_window_secs: 86_400,
+            max_gas: 1_000_000,
+        }
+    }
+}
+
+/// ERC-4337 账户抽象（EntryPoint v0.6 + SimpleAccount）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountAbstractionConfig {
+    /// EntryPoint 合约address（各链相同）
+    pub entry_point: String,
+    /// SimpleAccountFactory address；账户未部署时用于 initCode 与反事实address
+    pub account_factory: String,
+    /// `createAccount(owner, salt)` 的 salt
+    pub account_salt: u64,
+    /// network -> bundler RPC URL；未配置的network不支持 AA 发送
+    pub bundler_urls: HashMap<String, String>,
+    /// 轮询 `eth_getUserOperationReceipt` 的间隔（秒）
+    pub receipt_poll_secs: u64,
+    /// 超过此时长（秒）后台不再轮询；之后查询接口仍会按需刷新
+    pub receipt_timeout_secs: u64,
+}
+
+impl Default for AccountAbstractionConfig {
+    fn default() -> Self {
+        Self {
+            entry_point: "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".to_string(),
+            account_factory: "0x9406Cc6185a346906296840746125a0E44976454".to_string(),
+            account_salt: 0,
+            bundler_urls: HashMap::new(),
+            receipt_poll_secs: 5,
+            receipt_timeout_secs: 600,
+        }
+    }
+}
+
+/// 多实例部署（多个 server 共享一个数据库）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClusterConfig {
+    /// 为 true 时 nonce 预留在 (network, address) 租约内进行
+    pub multi_instance: bool,
+    /// 调度任务租约 = 任务间隔 + 此余量（秒）；持有者崩溃后最多这么久被接管
+    pub lease_grace_secs: u64,
+    /// nonce 预留租约（毫秒）
+    pub nonce_lease_ms: u64,
+    /// wallet列表/元数据缓存的最长寿命（秒）；epoch 读取失败时只靠它过期
+    pub wallet_cache_ttl_secs: u64,
+    /// 两次 `cache_epochs` 检查的最小间隔（毫秒），即其他实例改动最迟多久可见
+    pub cache_epoch_check_ms: u64,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            multi_instance: false,
+            lease_grace_secs: 30,
+            nonce_lease_ms: 2_000,
+            wallet_cache_ttl_secs: 30,
+            cache_epoch_check_ms: 1_000,
+        }
+    }
+}
+
+/// 后台任务运行器（`ops::jobs`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobsConfig {
+    /// 失败或 panic 后第一次重试前的等待（秒），之后每次翻倍
+    pub restart_base_secs: u64,
+    /// 重试等待上限（秒）
+    pub restart_max_secs: u64,
+    /// 连续失败达到此次数即在 `/api/admin/jobs` 中标为不健康
+    pub unhealthy_after_failures: u32,
+    /// 关闭时等待进行中任务结束的时长（秒），超时后强制中止
+    pub drain_timeout_secs: u64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self { restart_base_secs: 5, restart_max_secs: 300, unhealthy_after_failures: 3, drain_timeout_secs: 10 }
+    }
+}
+
+/// 存储层单条查询的超时与连接池采样
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseQueryConfig {
+    /// 发送路径上的写入（nonce 预留、交易记录）的超时（毫秒），含等待连接的时间
+    pub hot_timeout_ms: u64,
+    /// 其余受保护查询的超时（毫秒）
+    pub standard_timeout_ms: u64,
+    /// 统计与导出查询的超时（毫秒）；调用处需显式选择此类别
+    pub analytics_timeout_ms: u64,
+    /// 耗时达到此值的查询记入慢查询日志（毫秒），不记录参数
+    pub slow_query_ms: u64,
+    /// 连接池大小、空闲连接数与获取等待的采样间隔（秒）；0 关闭采样
+    pub pool_sample_interval_secs: u64,
+}
+
+impl Default for DatabaseQueryConfig {
+    fn default() -> Self {
+        Self {
+            hot_timeout_ms: 2_000,
+            standard_timeout_ms: 10_000,
+            analytics_timeout_ms: 30_000,
+            slow_query_ms: 500,
+            pool_sample_interval_secs: 5,
+        }
+    }
+}
+
+/// SQLite WAL 管理：连接参数与定期 checkpoint / 文件大小采样
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WalConfig {
+    /// 定期 `wal_checkpoint(TRUNCATE)` 与大小采样
+    pub enabled: bool,
+    /// checkpoint 与采样间隔（秒）
+    pub checkpoint_interval_secs: u64,
+    /// 写连接池中使用中的连接超过此数时视为繁忙，本轮 checkpoint 推迟
+    pub busy_connections: u32,
+    /// 有读事务阻塞（checkpoint 返回 busy）时的重试次数
+    pub checkpoint_retries: u32,
+    /// 两次重试之间的等待（毫秒）
+    pub retry_delay_ms: u64,
+    /// 连接级 `PRAGMA journal_size_limit`（字节）；checkpoint 后 WAL 截断到此大小
+    pub journal_size_limit_bytes: Option<i64>,
+    /// 连接级 `PRAGMA wal_autocheckpoint`（页）
+    pub wal_autocheckpoint_pages: Option<u32>,
+}
+
+impl Default for WalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            checkpoint_interval_secs: 300,
+            busy_connections: 4,
+            checkpoint_retries: 3,
+            retry_delay_ms: 200,
+            journal_size_limit_bytes: Some(64 * 1024 * 1024),
+            wal_autocheckpoint_pages: Some(1000),
+        }
+    }
+}
+
+/// 发送前的收款方检查：原生币或 ERC-20 转账的收款方是合约时要求显式确认
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecipientGuardConfig {
+    /// 关闭后不查询 `eth_getCode`，所有收款方直接放行
+    pub enabled: bool,
+    /// `eth_getCode` 结果的缓存时长（秒）
+    pub code_cache_ttl_secs: u64,
+    /// 已知代币合约：network → 符号 → 合约address
+    pub token_contracts: HashMap<String, std::collections::BTreeMap<String, String>>,
+    /// 有意交互的协议合约（路由、跨链桥）：network → address；转账给它们无需确认
+    pub interaction_allowlist: HashMap<String, Vec<String>>,
+}
+
+impl Default for RecipientGuardConfig {
+    fn default() -> Self {
+        let mainnet_tokens = [
+            ("USDC", "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            ("USDT", "0xdAC17F958D2ee523a2206206994597C13D831ec7"),
+            ("DAI", "0x6B175474E89094C44Da98b954EedeAC495271d0F"),
+            ("WETH", "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+        ];
+        let token_contracts = HashMap::from([(
+            "eth".to_string(),
+            mainnet_tokens.iter().map(|(symbol, address)| (symbol.to_string(), address.to_string())).collect(),
+        )]);
+        Self { enabled: true, code_cache_ttl_secs: 600, token_contracts, interaction_allowlist: HashMap::new() }
+    }
+}
+
+/// 储备证明报告（`POST /api/admin/reports/proof_of_reserves`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReserveReportConfig {
+    /// 签署报告的托管wallet（EIP-191）；其Password随每次生成请求提供。未配置时无法生成报告
+    pub signing_wallet: Option<String>,
+    /// 统计原生币余额的 EVM network
+    pub networks: Vec<String>,
+    /// 额外统计的 ERC-20：network → 符号 → 合约address
+    pub tokens: HashMap<String, std::collections::BTreeMap<String, String>>,
+    /// 并发余额查询上限
+    pub concurrency: usize,
+}
+
+impl Default for ReserveReportConfig {
+    fn default() -> Self {
+        Self { signing_wallet: None, networks: vec!["eth".to_string()], tokens: HashMap::new(), concurrency: 8 }
+    }
+}
+
+/// 昂贵操作（服务端sign、KDF 解锁、备份/导出）的准入控制（`api::admission`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdmissionConfig {
+    /// 全局同时进行的昂贵操作上限
+    pub max_in_flight: usize,
+    /// 单个wallet同时进行的上限，避免一个wallet的突发占满全局额度
+    pub per_wallet_in_flight: usize,
+    /// 等待队列长度上限，队列满时直接返回 429
+    pub max_queued: usize,
+    /// 单个wallet在队列中的上限
+    pub per_wallet_queued: usize,
+    /// 排队等待上限（毫秒）；同时不超过所在路由的请求超时
+    pub max_wait_ms: u64,
+    /// 429 / 503 响应中的 `Retry-After`（秒）
+    pub retry_after_secs: u64,
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight: 16,
+            per_wallet_in_flight: 2,
+            max_queued: 64,
+            per_wallet_queued: 8,
+            max_wait_ms: 5_000,
+            retry_after_secs: 1,
+        }
+    }
+}
+
+/// 导入已有wallet时回填链上历史（`POST /api/wallets/:name/backfill`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackfillConfig {
+    pub enabled: bool,
+    /// 后台任务检查待回填游标的间隔（秒）
+    pub poll_interval_secs: u64,
+    /// 每批扫描的区块数，也是 ERC-20 Transfer 日志单次查询的区块范围；每批与游标在同一事务中提交
+    pub blocks_per_batch: u64,
+    /// network → Etherscan 兼容的浏览器 API；配置了的network用它代替逐块扫描
+    pub explorers: HashMap<String, ExplorerApiConfig>,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self { enabled: true, poll_interval_secs: 30, blocks_per_batch: 500, explorers: HashMap::new() }
+    }
+}
+
+/// Etherscan 兼容 API（`module=account&action=txlist` 与 `module=logs&action=getLogs`）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExplorerApiConfig {
+    /// 例如 `https://api.etherscan.io/api`
+    pub url: String,
+    /// 保存 API key 的环境变量名；未配置时不带 key 请求
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    /// 出站配额，与 RPC 节点使用同样的令牌桶；未配置时不限流
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RpcRateLimit>,
+}
+
+/// 运行时功能开关（`/api/admin/flags`）；数据库中的规则优先于这里的默认值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeatureFlagsConfig {
+    /// 两次读取 `feature_flags` cache epoch 的最小间隔（毫秒），即其他实例的改动最迟多久生效
+    pub epoch_check_ms: u64,
+    /// 开关名 → 默认值，覆盖代码中声明的默认值；未声明的开关名在启动时报错
+    pub defaults: HashMap<String, bool>,
+}
+
+impl Default for FeatureFlagsConfig {
+    fn default() -> Self {
+        Self { epoch_check_ms: 1_000, defaults: HashMap::new() }
+    }
+}
+
+/// 代币配置：非标准 ERC-20 的行为（`/api/admin/tokens` 中的设置优先）与跨链桥可用的资产
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenRegistryConfig {
+    /// 网络 → 合约address → 行为；未列出的 token 按标准 ERC-20 处理
+    pub behaviors: HashMap<String, HashMap<String, TokenBehavior>>,
+    /// 网络 → 符号 → 资产定义；跨链桥的两端都须在这里找到同一资产。配置后整体替换内置的主网列表
+    pub assets: HashMap<String, BTreeMap<String, TokenAssetConfig>>,
+}
+
+/// 某条链上的一个资产
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenAssetConfig {
+    /// 合约address；链原生币不填
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    pub decimals: u32,
+    /// 跨链对应的资产符号，不填即为符号本身（如 polygon 上的 WETH 填 `ETH`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+}
+
+impl TokenAssetConfig {
+    fn native(decimals: u32) -> Self {
+        Self { address: None, decimals, asset: None }
+    }
+
+    fn erc20(address: &str, decimals: u32) -> Self {
+        Self { address: Some(address.to_string()), decimals, asset: None }
+    }
+}
+
+impl Default for TokenRegistryConfig {
+    /// 内置 eth / bsc / polygon 主网上桥接常用的资产；注意 bsc 上的 USDC、USDT 是 18 位小数
+    fn default() -> Self {
+        let eth = BTreeMap::from([
+            ("ETH".to_string(), TokenAssetConfig::native(18)),
+            ("USDC".to_string(), TokenAssetConfig::erc20("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", 6)),
+            ("USDT".to_string(), TokenAssetConfig::erc20("0xdAC17F958D2ee523a2206206994597C13D831ec7", 6)),
+            ("DAI".to_string(), TokenAssetConfig::erc20("0x6B175474E89094C44Da98b954EedeAC495271d0F", 18)),
+            ("BNB".to_string(), TokenAssetConfig::erc20("0xB8c77482e45F1F44dE1745F52C74426C631bDD52", 18)),
+            ("MATIC".to_string(), TokenAssetConfig::erc20("0x7D1AfA7B718fb893dB30A3aBc0Cfc608AaCfeBB0", 18)),
+        ]);
+        let bsc = BTreeMap::from([
+            ("BNB".to_string(), TokenAssetConfig::native(18)),
+            ("USDC".to_string(), TokenAssetConfig::erc20("0x8AC76a51cc950d9822D68b83fE1Ad97B32Cd580d", 18)),
+            ("USDT".to_string(), TokenAssetConfig::erc20("0x55d398326f99059fF775485246999027B3197955", 18)),
+            ("DAI".to_string(), TokenAssetConfig::erc20("0x1AF3F329e8BE154074D8769D1FFa4eE058B1DBc3", 18)),
+            ("ETH".to_string(), TokenAssetConfig::erc20("0x2170Ed0880ac9A755fd29B2688956BD959F933F8", 18)),
+        ]);
+        let polygon = BTreeMap::from([
+            ("MATIC".to_string(), TokenAssetConfig::native(18)),
+            ("USDC".to_string(), TokenAssetConfig::erc20("0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359", 6)),
+            ("USDT".to_string(), TokenAssetConfig::erc20("0xc2132D05D31c914a87C6611C10748AEb04B58e8F", 6)),
+            ("DAI".to_string(), TokenAssetConfig::erc20("0x8f3Cf7ad23Cd3CaDbD9735AFf958023239c6A063", 18)),
+            (
+                "WETH".to_string(),
+                TokenAssetConfig {
+                    asset: Some("ETH".to_string()),
+                    ..TokenAssetConfig::erc20("0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619", 18)
+                },
+            ),
+        ]);
+        let assets =
+            HashMap::from([("eth".to_string(), eth), ("bsc".to_string(), bsc), ("polygon".to_string(), polygon)]);
+        Self { behaviors: HashMap::new(), assets }
+    }
+}
+
+/// 时间锁transaction（`/api/wallets/:name/timelocks`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimelockConfig {
+    /// 预留 nonce 段的起点在创建时链上 pending nonce 之上的距离；这段空间留给解锁前的普通发送
+    pub lane_offset: u64,
+    /// 预留 nonce 段的长度，即同一address同时存在的时间锁上限；段内的 nonce 不会分配给普通发送
+    pub lane_size: u64,
+    /// 未指定过期时间时，解锁后仍可释放的秒数
+    pub default_release_window_secs: u64,
+    /// 解锁条件最远可以设在多少秒之后
+    pub max_lock_secs: u64,
+    /// 释放时重新校验：gas limit 上限
+    pub max_gas_limit: u64,
+}
+
+impl Default for TimelockConfig {
+    fn default() -> Self {
+        Self {
+            lane_offset: 64,
+            lane_size: 16,
+            default_release_window_secs: 7 * 24 * 3600,
+            max_lock_secs: 366 * 24 * 3600,
+            max_gas_limit: 1_000_000,
+        }
+    }
+}
+
+/// 跨链桥transaction状态轮询
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BridgePollerConfig {
+    /// 是否在后台推进 Initiated / InTransit 状态的跨链transaction
+    pub enabled: bool,
+    /// 两轮轮询之间的秒数
+    pub interval_secs: u64,
+    /// 每轮最多检查的transaction数
+    pub batch_size: usize,
+    /// 同时向桥查询状态的transaction数上限
+    pub max_concurrent_checks: usize,
+    /// 单笔transaction查询失败后的首次退避秒数，之后每次失败翻倍
+    pub backoff_base_secs: u64,
+    /// 单笔transaction退避的上限秒数
+    pub backoff_max_secs: u64,
+}
+
+impl Default for BridgePollerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+            batch_size: 100,
+            max_concurrent_checks: 4,
+            backoff_base_secs: 60,
+            backoff_max_secs: 3600,
+        }
+    }
+}
+
 /// wallet配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletConfig {
@@ -119,6 +1320,90 @@ pub struct WalletConfig {
     /// 安全配置
     #[serde(default)]
     pub security: SecurityConfig,
+
+    /// 跨链桥后端（默认 real）
+    #[serde(default)]
+    pub bridge_backend: BridgeBackend,
+
+    /// 余额快照
+    #[serde(default)]
+    pub balance_snapshots: BalanceSnapshotConfig,
+
+    /// 数据库自动备份
+    #[serde(default)]
+    pub backups: BackupConfig,
+
+    /// 元transaction中继（EIP-2771）
+    #[serde(default)]
+    pub relay: RelayConfig,
+
+    /// 多实例部署
+    #[serde(default)]
+    pub cluster: ClusterConfig,
+
+    /// ERC-4337 账户抽象
+    #[serde(default)]
+    pub account_abstraction: AccountAbstractionConfig,
+
+    /// 手续费历史与 gas 价格采样
+    #[serde(default)]
+    pub fee_tracking: FeeTrackingConfig,
+
+    /// 超过审查阈值的transaction需第二人审批
+    #[serde(default)]
+    pub approvals: ApprovalConfig,
+
+    /// 法币估值
+    #[serde(default)]
+    pub pricing: PricingConfig,
+
+    /// 后台任务的重试、健康判定与关闭
+    #[serde(default)]
+    pub jobs: JobsConfig,
+
+    /// SQLite WAL checkpoint 与数据库大小监控
+    #[serde(default)]
+    pub wal: WalConfig,
+
+    /// 存储查询超时、慢查询日志与连接池指标
+    #[serde(default)]
+    pub database_queries: DatabaseQueryConfig,
+
+    /// 收款方为合约时的发送确认
+    #[serde(default)]
+    pub recipient_guard: RecipientGuardConfig,
+
+    /// 储备证明报告
+    #[serde(default)]
+    pub reserve_reports: ReserveReportConfig,
+
+    /// 昂贵操作的并发准入
+    #[serde(default)]
+    pub admission: AdmissionConfig,
+
+    /// 链上历史回填
+    #[serde(default)]
+    pub backfill: BackfillConfig,
+
+    /// 运行时功能开关
+    #[serde(default)]
+    pub feature_flags: FeatureFlagsConfig,
+
+    /// 非标准 ERC-20 token
+    #[serde(default)]
+    pub tokens: TokenRegistryConfig,
+
+    /// 时间锁transaction
+    #[serde(default)]
+    pub timelocks: TimelockConfig,
+
+    /// 跨链桥transaction状态轮询
+    #[serde(default)]
+    pub bridge_poller: BridgePollerConfig,
+
+    /// 服务端sign发送前的异常检测
+    #[serde(default)]
+    pub anomaly_detection: AnomalyDetectionConfig,
 }
 
 impl Default for WalletConfig {
@@ -128,12 +1413,34 @@ impl Default for WalletConfig {
                 database_url: "sqlite://wallet.db".to_string(),
                 max_connections: Some(10),
                 connection_timeout_seconds: Some(30),
+                read_database_url: None,
             },
             blockchain: BlockchainConfig::default(),
             quantum_safe: false,
             multi_sig_threshold: 2,
             derivation: DerivationConfig::default(),
             security: SecurityConfig::default(),
+            bridge_backend: BridgeBackend::default(),
+            balance_snapshots: BalanceSnapshotConfig::default(),
+            backups: BackupConfig::default(),
+            relay: RelayConfig::default(),
+            cluster: ClusterConfig::default(),
+            account_abstraction: AccountAbstractionConfig::default(),
+            fee_tracking: FeeTrackingConfig::default(),
+            approvals: ApprovalConfig::default(),
+            pricing: PricingConfig::default(),
+            jobs: JobsConfig::default(),
+            wal: WalConfig::default(),
+            database_queries: DatabaseQueryConfig::default(),
+            recipient_guard: RecipientGuardConfig::default(),
+            reserve_reports: ReserveReportConfig::default(),
+            admission: AdmissionConfig::default(),
+            backfill: BackfillConfig::default(),
+            feature_flags: FeatureFlagsConfig::default(),
+            tokens: TokenRegistryConfig::default(),
+            timelocks: TimelockConfig::default(),
+            bridge_poller: BridgePollerConfig::default(),
+            anomaly_detection: AnomalyDetectionConfig::default(),
         }
     }
 }
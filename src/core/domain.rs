@@ -1,5 +1,4 @@
 use anyhow::Result;
-use secrecy::{ExposeSecret, Secret};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use sha2::{Digest, Sha256};
@@ -30,6 +29,13 @@ pub struct Tx {
     pub gas_price: Option<String>,
     /// Optional gas limit (for Ethereum-like chains)
     pub gas_limit: Option<String>,
+    /// EIP-1559 fee cap per gas in wei; set together with `max_priority_fee_per_gas`
+    /// instead of `gas_price` to build a type-2 transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<String>,
+    /// EIP-1559 tip per gas in wei
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<String>,
     /// Optional data payload (for contract calls)
     pub data: Option<Vec<u8>>,
     /// Optional nonce (for replay protection)
@@ -55,6 +61,37 @@ impl Tx {
         Ok(crate::core::abi::abi_pack(selector, &[addr, amt]))
     }
 
+    /// Encode an ERC-20 approve calldata granting `spender` an allowance of
+    /// `amount_min_units`. The zero address is refused as spender.
+    /// selector: keccak256("approve(address,uint256)")[0..4] = 095ea7b3
+    pub fn encode_erc20_approve_calldata(spender_hex: &str, amount_min_units: &str) -> Result<Vec<u8>> {
+        let selector = crate::core::abi::selector_from_signature("approve(address,uint256)");
+        let spender = crate::core::abi::abi_word_address(spender_hex)
+            .map_err(|_| anyhow::anyhow!("Invalid ERC-20 address format for calldata"))?;
+        if spender == [0u8; 32] {
+            return Err(anyhow::anyhow!("Cannot approve the zero address"));
+        }
+        let amt = crate::core::abi::abi_word_uint256_from_str(amount_min_units)?;
+        Ok(crate::core::abi::abi_pack(selector, &[spender, amt]))
+    }
+
+    /// Encode an ERC-20 transferFrom calldata moving `amount_min_units` from
+    /// `from_hex` to `to_hex` out of the caller's allowance.
+    /// selector: keccak256("transferFrom(address,address,uint256)")[0..4] = 23b872dd
+    pub fn encode_erc20_transfer_from_calldata(
+        from_hex: &str,
+        to_hex: &str,
+        amount_min_units: &str,
+    ) -> Result<Vec<u8>> {
+        let selector = crate::core::abi::selector_from_signature("transferFrom(address,address,uint256)");
+        let from = crate::core::abi::abi_word_address(from_hex)
+            .map_err(|_| anyhow::anyhow!("Invalid ERC-20 address format for calldata"))?;
+        let to = crate::core::abi::abi_word_address(to_hex)
+            .map_err(|_| anyhow::anyhow!("Invalid ERC-20 address format for calldata"))?;
+        let amt = crate::core::abi::abi_word_uint256_from_str(amount_min_units)?;
+        Ok(crate::core::abi::abi_pack(selector, &[from, to, amt]))
+    }
+
     /// Encode a generic static ABI contract call from selector and 32-byte words.
     pub fn encode_contract_call_static(selector: [u8; 4], words: &[[u8; 32]]) -> Vec<u8> {
         crate::core::abi::abi_pack(selector, words)
@@ -91,6 +128,8 @@ impl Tx {
             network: network.to_string(),
             gas_price: None,
             gas_limit: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             data: None,
             nonce: None,
             chain_id: None,
@@ -115,6 +154,8 @@ impl Tx {
             network: network.to_string(),
             gas_price: None,
             gas_limit: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             data: None,
             nonce: None,
             chain_id: None,
@@ -140,6 +181,8 @@ impl Tx {
             network: network.to_string(),
             gas_price: None,
             gas_limit: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             data: Some(data),
             nonce: None,
             chain_id: None,
@@ -154,6 +197,22 @@ impl Tx {
         self
     }
 
+    /// Set EIP-1559 fee parameters (wei per gas), making this a type-2 transaction
+    pub fn with_eip1559_fees(
+        mut self,
+        max_fee_per_gas: &str,
+        max_priority_fee_per_gas: &str,
+    ) -> Self {
+        self.max_fee_per_gas = Some(max_fee_per_gas.to_string());
+        self.max_priority_fee_per_gas = Some(max_priority_fee_per_gas.to_string());
+        self
+    }
+
+    /// Whether any EIP-1559 fee field is set
+    pub fn is_eip1559(&self) -> bool {
+        self.max_fee_per_gas.is_some() || self.max_priority_fee_per_gas.is_some()
+    }
+
     /// Set nonce for replay protection
     pub fn with_nonce(mut self, nonce: u64) -> Self {
         self.nonce = Some(nonce);
@@ -221,6 +280,15 @@ impl Tx {
             }
         }
 
+        for fee in [&self.max_fee_per_gas, &self.max_priority_fee_per_gas].into_iter().flatten() {
+            if fee.len() > 30 {
+                return Err(anyhow::anyhow!("Fee per gas string too long"));
+            }
+            if !fee.chars().all(|c| c.is_ascii_digit()) {
+                return Err(anyhow::anyhow!("Invalid characters in fee per gas"));
+            }
+        }
+
         // Validate data payload size
         if let Some(data) = &self.data {
             if data.len() > 10000 {
@@ -357,8 +425,30 @@ impl Tx {
 
     /// Validate Ethereum-specific transaction fields
     fn validate_ethereum_transaction(&self) -> Result<()> {
-        // For Ethereum transactions, gas parameters are required
-        if self.gas_price.is_none() {
+        // For Ethereum transactions, gas parameters are required: either a legacy gas price
+        // or both EIP-1559 fee fields, never a mix of the two
+        if self.is_eip1559() {
+            if self.gas_price.is_some() {
+                return Err(anyhow::anyhow!(
+                    "Gas price cannot be combined with EIP-1559 fee fields"
+                ));
+            }
+            let (Some(max_fee), Some(priority_fee)) =
+                (&self.max_fee_per_gas, &self.max_priority_fee_per_gas)
+            else {
+                return Err(anyhow::anyhow!(
+                    "EIP-1559 transactions need both max_fee_per_gas and max_priority_fee_per_gas"
+                ));
+            };
+            let max_fee =
+                max_fee.parse::<u64>().map_err(|_| anyhow::anyhow!("Invalid max fee format"))?;
+            let priority_fee = priority_fee
+                .parse::<u64>()
+                .map_err(|_| anyhow::anyhow!("Invalid max priority fee format"))?;
+            if priority_fee > max_fee {
+                return Err(anyhow::anyhow!("Max priority fee exceeds max fee per gas"));
+            }
+        } else if self.gas_price.is_none() {
             return Err(anyhow::anyhow!("Gas price is required for Ethereum transactions"));
         }
         if self.gas_limit.is_none() {
@@ -449,6 +539,19 @@ impl Tx {
                 hasher.update(v.to_be_bytes());
             }
         }
+        // EIP-1559 fees are only tagged when present, so legacy transactions keep their hash
+        if let Some(max_fee) = &self.max_fee_per_gas {
+            if let Ok(v) = max_fee.parse::<u64>() {
+                hasher.update(b"|max_fee:");
+                hasher.update(v.to_be_bytes());
+            }
+        }
+        if let Some(priority_fee) = &self.max_priority_fee_per_gas {
+            if let Ok(v) = priority_fee.parse::<u64>() {
+                hasher.update(b"|max_priority_fee:");
+                hasher.update(v.to_be_bytes());
+            }
+        }
         if let Some(nonce) = self.nonce {
             hasher.update(b"|nonce:");
             hasher.update(nonce.to_be_bytes());
@@ -470,35 +573,104 @@ impl Tx {
         format!("{:x}", hasher.finalize())
     }
 
+    /// Build the ethers transaction for this Tx: a type-2 request when the EIP-1559 fee
+    /// fields are set, a legacy one otherwise. Unset gas/nonce/chain id stay unset.
+    pub fn to_typed_transaction(
+        &self,
+    ) -> Result<ethers::types::transaction::eip2718::TypedTransaction> {
+        use ethers::types::{
+            Address as EthAddress, Bytes, Eip1559TransactionRequest, TransactionRequest, U256,
+        };
+        use std::str::FromStr;
+
+        let address = |s: &str| {
+            EthAddress::from_str(s).map_err(|_| anyhow::anyhow!("Invalid Ethereum address: {}", s))
+        };
+        let wei = |s: &str| {
+            U256::from_dec_str(s).map_err(|_| anyhow::anyhow!("Invalid integer amount: {}", s))
+        };
+        let (to, value, data) = match &self.tx_type {
+            TransactionType::NativeTransfer => (address(&self.to)?, wei(&self.amount)?, None),
+            TransactionType::Erc20Transfer { token_address, token_amount } => {
+                let calldata = Self::encode_erc20_transfer_calldata(&self.to, token_amount)?;
+                (address(token_address)?, U256::zero(), Some(calldata))
+            }
+            TransactionType::ContractCall { contract_address, data, value } => (
+                address(contract_address)?,
+                wei(value.as_deref().unwrap_or("0"))?,
+                Some(data.clone()),
+            ),
+        };
+        let gas = self.gas_limit.as_deref().map(wei).transpose()?;
+
+        if self.is_eip1559() {
+            let mut tx = Eip1559TransactionRequest::new().to(to).value(value);
+            tx.max_fee_per_gas = self.max_fee_per_gas.as_deref().map(wei).transpose()?;
+            tx.max_priority_fee_per_gas =
+                self.max_priority_fee_per_gas.as_deref().map(wei).transpose()?;
+            tx.gas = gas;
+            tx.nonce = self.nonce.map(U256::from);
+            tx.chain_id = self.chain_id.map(Into::into);
+            tx.data = data.map(Bytes::from);
+            Ok(tx.into())
+        } else {
+            let mut tx = TransactionRequest::new().to(to).value(value);
+            tx.gas_price = self.gas_price.as_deref().map(wei).transpose()?;
+            tx.gas = gas;
+            tx.nonce = self.nonce.map(U256::from);
+            tx.chain_id = self.chain_id.map(Into::into);
+            tx.data = data.map(Bytes::from);
+            Ok(tx.into())
+        }
+    }
+
     /// Legacy constructor for backward compatibility
     pub fn new(_w: &crate::mvp::Wallet, to: &str, amount: u64) -> Self {
         Self::new_native_transfer(to, &amount.to_string(), "unknown")
     }
 }
 
-/// Private key wrapper (32 bytes) with secrecy::Secret for automatic zeroization and display-hiding
-pub struct PrivateKey(Secret<[u8; 32]>);
+/// Private key wrapper (32 bytes), held in a zeroizing [`SecretVec`](crate::security::SecretVec)
+pub struct PrivateKey(crate::security::SecretVec);
 impl PrivateKey {
     pub fn new(k: [u8; 32]) -> Self {
-        Self(Secret::new(k))
+        Self(crate::security::SecretVec::new(k.to_vec()))
     }
     /// Expose the underlying bytes (read-only) when strictly necessary.
     pub fn as_bytes(&self) -> &[u8; 32] {
-        self.0.expose_secret()
+        self.0.as_slice().try_into().expect("private key is 32 bytes")
     }
 
     /// Scoped access to the underlying secret bytes. Prefer this over `as_bytes()` so
     /// callers can't accidentally hold on to or clone secret data outside a small scope.
+    /// The bytes are handed out as [`TaintedBytes`](crate::security::memory_protection::TaintedBytes),
+    /// so test builds record a copy into a plain `Vec<u8>` made inside the closure.
     ///
     /// Example:
-    ///   pk.with_secret(|b| { /* use b: &[u8;32] here only */ });
+    ///   pk.with_secret(|b| { /* use b: &[u8] (32 bytes) here only */ });
     pub fn with_secret<F, R>(&self, f: F) -> R
     where
-        F: FnOnce(&[u8; 32]) -> R,
+        F: FnOnce(&crate::security::memory_protection::TaintedBytes) -> R,
     {
-        // Expose the secret to the closure for immediate use. This does not allocate a
-        // new buffer and encourages scoped usage patterns.
-        f(self.0.expose_secret())
+        // Release builds borrow the key in place; test builds wrap a zeroizing copy
+        // that carries the leak-report label and is dropped when the closure returns
+        #[cfg(not(any(test, feature = "test-env")))]
+        {
+            f(crate::security::memory_protection::TaintedBytes::borrowed(&self.0))
+        }
+        #[cfg(any(test, feature = "test-env"))]
+        {
+            f(&self.tainted())
+        }
+    }
+
+    /// Owned copy for APIs that take [`TaintedBytes`](crate::security::memory_protection::TaintedBytes);
+    /// test builds track where the copy ends up.
+    pub fn tainted(&self) -> crate::security::memory_protection::TaintedBytes {
+        crate::security::memory_protection::TaintedBytes::new(
+            zeroize::Zeroizing::new(self.0.to_vec()),
+            "PrivateKey",
+        )
     }
 
     /// Try to construct a PrivateKey from a byte slice (must be 32 bytes).
@@ -513,10 +685,8 @@ impl PrivateKey {
 }
 impl Zeroize for PrivateKey {
     fn zeroize(&mut self) {
-        // overwrite the inner secret by replacing with a zeroed array
-        let zero = [0u8; 32];
-        // ensure previous content is dropped
-        self.0 = Secret::new(zero);
+        // overwrite the key in place; the length stays 32 so `as_bytes` keeps working
+        self.0.as_mut_slice().zeroize();
     }
 }
 impl Drop for PrivateKey {
@@ -652,6 +822,65 @@ mod tests {
         assert!(msg.contains("integer") || msg.contains("Uint256 must be"));
     }
 
+    #[test]
+    fn test_encode_erc20_approve_calldata_matches_known_encoding() {
+        // unlimited approval of the Uniswap V2 router
+        let data = Tx::encode_erc20_approve_calldata(
+            "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D",
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935",
+        )
+        .expect("calldata");
+        let expected = concat!(
+            "095ea7b3",
+            "0000000000000000000000007a250d5630b4cf539739df2c5dacb4c659f2488d",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        );
+        assert_eq!(hex::encode(&data), expected);
+
+        let data = Tx::encode_erc20_approve_calldata("0x1111111111111111111111111111111111111111", "0")
+            .expect("revoking is an approval of zero");
+        let expected = concat!(
+            "095ea7b3",
+            "0000000000000000000000001111111111111111111111111111111111111111",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        );
+        assert_eq!(hex::encode(&data), expected);
+    }
+
+    #[test]
+    fn test_encode_erc20_approve_calldata_rejects_zero_spender_and_bad_amount() {
+        let err = Tx::encode_erc20_approve_calldata("0x0000000000000000000000000000000000000000", "1").unwrap_err();
+        assert!(err.to_string().contains("zero address"));
+        let spender = "0x1111111111111111111111111111111111111111";
+        for amount in ["", "-1", "1.5", "1e18", "0x10"] {
+            assert!(Tx::encode_erc20_approve_calldata(spender, amount).is_err(), "{}", amount);
+        }
+        // 2^256
+        let too_big = "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+        assert!(Tx::encode_erc20_approve_calldata(spender, too_big).is_err());
+    }
+
+    #[test]
+    fn test_encode_erc20_transfer_from_calldata_matches_known_encoding() {
+        let data = Tx::encode_erc20_transfer_from_calldata(
+            "0x1111111111111111111111111111111111111111",
+            "2222222222222222222222222222222222222222",
+            "1000000000000000000",
+        )
+        .expect("calldata");
+        let expected = concat!(
+            "23b872dd",
+            "0000000000000000000000001111111111111111111111111111111111111111",
+            "0000000000000000000000002222222222222222222222222222222222222222",
+            "0000000000000000000000000000000000000000000000000de0b6b3a7640000",
+        );
+        assert_eq!(hex::encode(&data), expected);
+
+        let err = Tx::encode_erc20_transfer_from_calldata("0x1234", "0x2222222222222222222222222222222222222222", "1")
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid ERC-20 address"));
+    }
+
     #[test]
     fn test_wallet_from_mnemonic() {
         let wallet = crate::mvp::Wallet::from_mnemonic("test mnemonic").unwrap();
@@ -785,6 +1014,8 @@ mod tests {
             network: "polygon".to_string(),
             gas_price: Some("20000000000".to_string()),
             gas_limit: Some("21000".to_string()),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             nonce: Some(0),
             chain_id: Some(137),
             data: None,
@@ -805,6 +1036,8 @@ mod tests {
             network: "eth".to_string(),
             gas_price: Some("20000000000".to_string()),
             gas_limit: Some("21000".to_string()),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             data: None,
             nonce: Some(5),
             chain_id: Some(1),
@@ -843,6 +1076,123 @@ mod tests {
         assert_eq!(original_tx.chain_id, deserialized_tx.chain_id);
     }
 
+    fn fixed_legacy_tx() -> Tx {
+        let mut tx = Tx::new_native_transfer(
+            "0x742d35Cc6634C0532925a3b844Bc454e4438f44e",
+            "1000000000000000000",
+            "eth",
+        )
+        .with_gas("20000000000", "21000")
+        .with_nonce(5)
+        .with_chain_id(1);
+        tx.timestamp = "2024-01-01T00:00:00Z".parse().unwrap();
+        tx
+    }
+
+    #[test]
+    fn test_tx_hash_of_legacy_tx_is_stable() {
+        // 未设置 EIP-1559 字段时哈希与引入这些字段之前一致
+        let expected = "43e7f4191151ed412c2185f49daffbd8bdd3f4cee384bcb344f0aaff89e06025";
+        assert_eq!(fixed_legacy_tx().hash(), expected);
+
+        // 旧格式的 JSON（没有新字段）仍可反序列化，哈希不变
+        let old_json = r#"{"tx_type":"NativeTransfer","to":"0x742d35Cc6634C0532925a3b844Bc454e4438f44e",
+            "amount":"1000000000000000000","network":"eth","gas_price":"20000000000","gas_limit":"21000",
+            "data":null,"nonce":5,"chain_id":1,"timestamp":"2024-01-01T00:00:00Z"}"#;
+        let tx = Tx::deserialize(old_json.as_bytes()).unwrap();
+        assert!(!tx.is_eip1559());
+        assert_eq!(tx.hash(), expected);
+
+        // 序列化结果不出现新字段
+        let serialized = String::from_utf8(fixed_legacy_tx().serialize().unwrap()).unwrap();
+        assert!(!serialized.contains("max_fee_per_gas"));
+    }
+
+    #[test]
+    fn test_tx_eip1559_fees_are_hashed() {
+        let mut tx = fixed_legacy_tx();
+        tx.gas_price = None;
+        let base = tx.hash();
+        let with_fees = tx.clone().with_eip1559_fees("30000000000", "2000000000");
+        assert_ne!(with_fees.hash(), base);
+        assert_ne!(
+            with_fees.hash(),
+            tx.clone().with_eip1559_fees("30000000000", "1000000000").hash()
+        );
+        // 两个字段互换不会得到相同的哈希
+        assert_ne!(with_fees.hash(), tx.with_eip1559_fees("2000000000", "30000000000").hash());
+    }
+
+    #[test]
+    fn test_tx_eip1559_roundtrip_serialization() {
+        let mut original_tx = fixed_legacy_tx();
+        original_tx.gas_price = None;
+        let original_tx = original_tx.with_eip1559_fees("30000000000", "2000000000");
+        assert!(original_tx.validate().is_ok());
+
+        let deserialized_tx = Tx::deserialize(&original_tx.serialize().unwrap()).unwrap();
+        assert_eq!(deserialized_tx.max_fee_per_gas.as_deref(), Some("30000000000"));
+        assert_eq!(deserialized_tx.max_priority_fee_per_gas.as_deref(), Some("2000000000"));
+        assert_eq!(deserialized_tx.gas_price, None);
+        assert_eq!(deserialized_tx.hash(), original_tx.hash());
+    }
+
+    #[test]
+    fn test_tx_validate_eip1559_fees() {
+        // gas_price 与 EIP-1559 字段不能混用
+        let mixed = fixed_legacy_tx().with_eip1559_fees("30000000000", "2000000000");
+        assert!(mixed.validate().unwrap_err().to_string().contains("cannot be combined"));
+
+        let mut tx = fixed_legacy_tx();
+        tx.gas_price = None;
+        let mut partial = tx.clone();
+        partial.max_fee_per_gas = Some("30000000000".to_string());
+        assert!(partial.validate().unwrap_err().to_string().contains("need both"));
+
+        let inverted = tx.clone().with_eip1559_fees("1000000000", "2000000000");
+        assert!(inverted.validate().unwrap_err().to_string().contains("exceeds max fee"));
+
+        let malformed = tx.with_eip1559_fees("30 gwei", "2000000000");
+        assert!(malformed.validate().is_err());
+        assert!(malformed.serialize().is_err());
+    }
+
+    #[test]
+    fn test_tx_to_typed_transaction() {
+        use ethers::types::transaction::eip2718::TypedTransaction;
+
+        let legacy = fixed_legacy_tx().to_typed_transaction().unwrap();
+        assert!(matches!(legacy, TypedTransaction::Legacy(_)));
+        assert_eq!(legacy.gas_price(), Some(20_000_000_000u64.into()));
+
+        let mut tx = fixed_legacy_tx();
+        tx.gas_price = None;
+        let typed =
+            tx.with_eip1559_fees("30000000000", "2000000000").to_typed_transaction().unwrap();
+        let TypedTransaction::Eip1559(request) = &typed else {
+            panic!("expected a type-2 transaction, got {:?}", typed);
+        };
+        assert_eq!(request.max_fee_per_gas, Some(30_000_000_000u64.into()));
+        assert_eq!(request.max_priority_fee_per_gas, Some(2_000_000_000u64.into()));
+        assert_eq!(typed.nonce(), Some(&5u64.into()));
+        assert_eq!(typed.chain_id(), Some(1u64.into()));
+        assert_eq!(typed.gas(), Some(&21_000u64.into()));
+
+        // ERC-20 转账发往代币合约，收款方编码在 calldata 中
+        let token = "0x1111111111111111111111111111111111111111";
+        let erc20 = Tx::new_erc20_transfer(
+            token,
+            "0x742d35Cc6634C0532925a3b844Bc454e4438f44e",
+            "42",
+            "eth",
+        )
+        .with_eip1559_fees("30000000000", "2000000000")
+        .to_typed_transaction()
+        .unwrap();
+        assert_eq!(erc20.to_addr(), Some(&token.parse().unwrap()));
+        assert_eq!(erc20.data().map(|d| d.len()), Some(68));
+    }
+
     #[test]
     fn test_private_key_new() {
         let key = [1u8; 32];
@@ -850,6 +1200,21 @@ mod tests {
         assert_eq!(*pk.as_bytes(), key);
     }
 
+    #[test]
+    fn test_private_key_with_secret_records_plain_copies() {
+        use crate::security::memory_protection::take_secret_escapes;
+
+        let pk = PrivateKey::new([1u8; 32]);
+        assert_eq!(pk.with_secret(|b| b.len()), 32);
+        assert!(take_secret_escapes().is_empty());
+
+        let copied = pk.with_secret(|b| b.to_vec());
+        assert_eq!(copied, vec![1u8; 32]);
+        let escapes = take_secret_escapes();
+        assert_eq!(escapes.len(), 1);
+        assert_eq!((escapes[0].label, escapes[0].allowed), ("PrivateKey", None));
+    }
+
     #[test]
     fn test_public_key_new() {
         let key = [2u8; 33];
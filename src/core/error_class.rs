@@ -0,0 +1,242 @@
+//! Stable error taxonomy for metrics, the events journal and incident reports.
+//!
+//! Error messages change from release to release and often carry request
+//! specifics, so dashboards group failures by [`ErrorClass`] instead. The
+//! class names are part of the metrics and journal contract: add new ones,
+//! never rename or reuse them.
+//!
+//! Classification runs on every failed request, so it only matches on enum
+//! variants and `downcast_ref`s and never formats or inspects messages.
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::errors::WalletError;
+use crate::storage::QueryError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    /// The request or its input was rejected
+    Validation,
+    /// Missing or wrong credentials, password or permission
+    Unauthorized,
+    NotFound,
+    /// The target exists or is in a state that does not allow the operation
+    Conflict,
+    /// Throttled locally or by an upstream quota
+    RateLimited,
+    InsufficientFunds,
+    /// A blockchain node or bridge returned an error or was unreachable
+    ChainRpc,
+    Timeout,
+    Database,
+    Serialization,
+    /// Encryption, decryption, key derivation or signing failed
+    Crypto,
+    /// An HTTP dependency other than a chain node (price feed, bundler, ...)
+    ExternalHttp,
+    Io,
+    /// The service or a feature is not available right now
+    Unavailable,
+    Config,
+    Internal,
+    /// No database connection became free in time
+    PoolExhausted,
+}
+
+impl ErrorClass {
+    /// Every class, in declaration order.
+    pub const ALL: [ErrorClass; 17] = [
+        ErrorClass::Validation,
+        ErrorClass::Unauthorized,
+        ErrorClass::NotFound,
+        ErrorClass::Conflict,
+        ErrorClass::RateLimited,
+        ErrorClass::InsufficientFunds,
+        ErrorClass::ChainRpc,
+        ErrorClass::Timeout,
+        ErrorClass::Database,
+        ErrorClass::Serialization,
+        ErrorClass::Crypto,
+        ErrorClass::ExternalHttp,
+        ErrorClass::Io,
+        ErrorClass::Unavailable,
+        ErrorClass::Config,
+        ErrorClass::Internal,
+        ErrorClass::PoolExhausted,
+    ];
+
+    /// Position in [`ErrorClass::ALL`], for per-class counters.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The metric label and serialized name.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorClass::Validation => "validation",
+            ErrorClass::Unauthorized => "unauthorized",
+            ErrorClass::NotFound => "not_found",
+            ErrorClass::Conflict => "conflict",
+            ErrorClass::RateLimited => "rate_limited",
+            ErrorClass::InsufficientFunds => "insufficient_funds",
+            ErrorClass::ChainRpc => "chain_rpc",
+            ErrorClass::Timeout => "timeout",
+            ErrorClass::Database => "database",
+            ErrorClass::Serialization => "serialization",
+            ErrorClass::Crypto => "crypto",
+            ErrorClass::ExternalHttp => "external_http",
+            ErrorClass::Io => "io",
+            ErrorClass::Unavailable => "unavailable",
+            ErrorClass::Config => "config",
+            ErrorClass::Internal => "internal",
+            ErrorClass::PoolExhausted => "pool_exhausted",
+        }
+    }
+
+    /// Best guess from an HTTP status alone, for responses whose handler did
+    /// not say what went wrong.
+    pub fn from_status(status: u16) -> ErrorClass {
+        match status {
+            401 | 403 => ErrorClass::Unauthorized,
+            404 | 410 => ErrorClass::NotFound,
+            409 | 412 => ErrorClass::Conflict,
+            429 => ErrorClass::RateLimited,
+            408 | 504 => ErrorClass::Timeout,
+            502 => ErrorClass::ChainRpc,
+            503 => ErrorClass::Unavailable,
+            400..=499 => ErrorClass::Validation,
+            _ => ErrorClass::Internal,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+pub fn classify_wallet_error(e: &WalletError) -> ErrorClass {
+    match e {
+        WalletError::ValidationError(_)
+        | WalletError::InvalidAddress(_)
+        | WalletError::InvalidPrivateKey(_)
+        | WalletError::InvalidAmount(_)
+        | WalletError::InvalidInput(_)
+        | WalletError::MnemonicError(_)
+        | WalletError::AddressError(_)
+        | WalletError::NetworkNotAllowed(_)
+        | WalletError::UnsupportedWalletKind(_) => ErrorClass::Validation,
+        WalletError::SecurityError(_) | WalletError::InvalidPassword(_) => ErrorClass::Unauthorized,
+        WalletError::NotFoundError(_) => ErrorClass::NotFound,
+        WalletError::KeyRotationRequired(_) => ErrorClass::Conflict,
+        WalletError::NetworkBusy(_) => ErrorClass::RateLimited,
+        WalletError::InsufficientFunds(_) => ErrorClass::InsufficientFunds,
+        WalletError::BlockchainError(_)
+        | WalletError::NetworkError(_)
+        | WalletError::BridgeError(_)
+        | WalletError::TransactionFailed(_) => ErrorClass::ChainRpc,
+        WalletError::TimeoutError(_) => ErrorClass::Timeout,
+        WalletError::StorageError(_) => ErrorClass::Database,
+        WalletError::SerializationError(_) | WalletError::DeserializationError(_) => ErrorClass::Serialization,
+        WalletError::CryptoError(_)
+        | WalletError::KeyDerivationError(_)
+        | WalletError::SigningFailed(_)
+        | WalletError::KeyGenerationFailed(_)
+        | WalletError::AddressGenerationFailed(_)
+        | WalletError::EncryptionError(_)
+        | WalletError::DecryptionError(_) => ErrorClass::Crypto,
+        WalletError::IoError(_) => ErrorClass::Io,
+        WalletError::NotImplemented(_) => ErrorClass::Unavailable,
+        WalletError::ConfigError(_) => ErrorClass::Config,
+        WalletError::InternalError(_)
+        | WalletError::AsyncError(_)
+        | WalletError::MemoryError(_)
+        | WalletError::GenericError(_)
+        | WalletError::Other(_) => ErrorClass::Internal,
+    }
+}
+
+/// Class of the first error in `e`'s chain that has a known type, or
+/// [`ErrorClass::Internal`] if none does.
+pub fn classify(e: &anyhow::Error) -> ErrorClass {
+    classify_or(e, ErrorClass::Internal)
+}
+
+/// Like [`classify`], with the class to report when nothing in the chain is
+/// recognized. Storage code mostly wraps driver errors into plain messages,
+/// so callers that know they were talking to the database pass
+/// [`ErrorClass::Database`] here.
+pub fn classify_or(e: &anyhow::Error, fallback: ErrorClass) -> ErrorClass {
+    e.chain().find_map(classify_source).unwrap_or(fallback)
+}
+
+fn classify_source(e: &(dyn std::error::Error + 'static)) -> Option<ErrorClass> {
+    if let Some(e) = e.downcast_ref::<WalletError>() {
+        return Some(classify_wallet_error(e));
+    }
+    if let Some(e) = e.downcast_ref::<QueryError>() {
+        return Some(match e {
+            QueryError::Timeout { .. } => ErrorClass::Timeout,
+            QueryError::PoolExhausted { .. } => ErrorClass::PoolExhausted,
+        });
+    }
+    if let Some(e) = e.downcast_ref::<sqlx::Error>() {
+        return Some(match e {
+            sqlx::Error::PoolTimedOut => ErrorClass::PoolExhausted,
+            sqlx::Error::RowNotFound => ErrorClass::NotFound,
+            sqlx::Error::Database(db) if db.is_unique_violation() => ErrorClass::Conflict,
+            sqlx::Error::ColumnDecode { .. } | sqlx::Error::Decode(_) => ErrorClass::Serialization,
+            _ => ErrorClass::Database,
+        });
+    }
+    if e.is::<ethers::providers::ProviderError>() {
+        return Some(ErrorClass::ChainRpc);
+    }
+    if let Some(e) = e.downcast_ref::<reqwest::Error>() {
+        return Some(if e.is_timeout() { ErrorClass::Timeout } else { ErrorClass::ExternalHttp });
+    }
+    if e.is::<serde_json::Error>() {
+        return Some(ErrorClass::Serialization);
+    }
+    if e.is::<tokio::time::error::Elapsed>() {
+        return Some(ErrorClass::Timeout);
+    }
+    if let Some(e) = e.downcast_ref::<std::io::Error>() {
+        return Some(match e.kind() {
+            std::io::ErrorKind::TimedOut => ErrorClass::Timeout,
+            std::io::ErrorKind::NotFound => ErrorClass::NotFound,
+            std::io::ErrorKind::PermissionDenied => ErrorClass::Unauthorized,
+            _ => ErrorClass::Io,
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_is_in_index_order_with_unique_labels() {
+        for (i, class) in ErrorClass::ALL.iter().enumerate() {
+            assert_eq!(class.index(), i);
+            assert_eq!(serde_json::to_value(class).unwrap(), class.as_str());
+        }
+        let mut labels: Vec<&str> = ErrorClass::ALL.iter().map(|c| c.as_str()).collect();
+        labels.sort_unstable();
+        labels.dedup();
+        assert_eq!(labels.len(), ErrorClass::ALL.len());
+    }
+
+    #[test]
+    fn test_from_status() {
+        assert_eq!(ErrorClass::from_status(400), ErrorClass::Validation);
+        assert_eq!(ErrorClass::from_status(422), ErrorClass::Validation);
+        assert_eq!(ErrorClass::from_status(403), ErrorClass::Unauthorized);
+        assert_eq!(ErrorClass::from_status(429), ErrorClass::RateLimited);
+        assert_eq!(ErrorClass::from_status(503), ErrorClass::Unavailable);
+        assert_eq!(ErrorClass::from_status(500), ErrorClass::Internal);
+    }
+}
@@ -65,6 +65,16 @@ pub enum WalletError {
     IoError(String),
     /// Deserialization errors.
     DeserializationError(String),
+    /// Signing key exceeded its rotation policy (usage or age).
+    KeyRotationRequired(String),
+    /// Network is outside the wallet's `allowed_networks`.
+    NetworkNotAllowed(String),
+    /// Outbound RPC quota exhausted (`NETWORK_BUSY`).
+    NetworkBusy(String),
+    /// Operation needs key material the wallet kind does not have (e.g. an HD tree on an imported key).
+    UnsupportedWalletKind(String),
+    /// Wrong password for an encrypted wallet keystore.
+    InvalidPassword(String),
     /// Generic errors.
     GenericError(String),
     /// Generic errors (legacy).
@@ -105,6 +115,11 @@ impl fmt::Display for WalletError {
             WalletError::MemoryError(msg) => write!(f, "Memory error: {}", msg),
             WalletError::IoError(msg) => write!(f, "IO error: {}", msg),
             WalletError::DeserializationError(msg) => write!(f, "Deserialization error: {}", msg),
+            WalletError::KeyRotationRequired(msg) => write!(f, "Key rotation required: {}", msg),
+            WalletError::NetworkNotAllowed(msg) => write!(f, "Network not allowed: {}", msg),
+            WalletError::NetworkBusy(msg) => write!(f, "Network busy: {}", msg),
+            WalletError::UnsupportedWalletKind(msg) => write!(f, "Unsupported wallet kind: {}", msg),
+            WalletError::InvalidPassword(msg) => write!(f, "Invalid password: {}", msg),
             WalletError::GenericError(msg) => write!(f, "Error: {}", msg),
             WalletError::Other(msg) => write!(f, "Error: {}", msg),
         }
@@ -134,6 +149,7 @@ impl WalletError {
             self,
             WalletError::NetworkError(_)
                 | WalletError::TimeoutError(_)
+                | WalletError::NetworkBusy(_)
         )
     }
 }
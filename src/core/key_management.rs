@@ -59,13 +59,11 @@ struct KeyLabelState {
     history: Vec<(u32, String)>,
 }
 
-fn load_master_enc_key() -> Result<[u8; 32]> {
+pub(crate) fn load_master_enc_key() -> Result<[u8; 32]> {
     // Prefer a strong, process-supplied secret for encrypting keys at rest.
     // CI/tests set WALLET_ENC_KEY via the test-env feature (see src/test_env.rs).
-    let enc_b64 = std::env::var("WALLET_ENC_KEY").map_err(|_| {
-        anyhow::anyhow!(
-            "WALLET_ENC_KEY not set. Provide a base64-encoded 32-byte key for key encryption."
-        )
+    let enc_b64 = crate::security::env_manager::secret_backend::master_key_encoded().map_err(|e| {
+        anyhow::anyhow!("{}. Provide a base64-encoded 32-byte key for key encryption.", e)
     })?;
     use base64::Engine as _;
     let engine = base64::engine::general_purpose::STANDARD;
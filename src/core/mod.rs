@@ -1,8 +1,14 @@
 pub mod abi;
+pub mod address_book;
+pub mod amount;
+pub mod clock;
 pub mod config;
 pub mod domain;
+pub mod error_class;
 pub mod errors;
+pub mod ids;
 pub mod result_ext;  // Result扩展工具
+pub mod rlp;
 pub mod key_management;
 pub mod key_manager;
 pub mod memory_protection;
@@ -12,7 +18,7 @@ pub mod wallet_info;
 pub mod wallet_manager;
 
 // 閲嶆柊瀵煎嚭鍏抽敭缁撴瀯
-pub use wallet_info::{SecureWalletData, WalletInfo};
+pub use wallet_info::{SecureWalletData, WalletInfo, WalletKeyKind};
 pub use wallet_manager::WalletManager;
 
 // Test-only helper modules for HD derivation probes/vectors
@@ -0,0 +1,299 @@
+//! Recursive Length Prefix encoding.
+//!
+//! A value is either a byte string or a list of values ([`Item`]). Encoding
+//! always produces the canonical form. Decoding is strict and rejects
+//! anything another encoder could not have produced for the same value:
+//!
+//! - a single byte below `0x80` wrapped in a string header,
+//! - a long-form header for a payload shorter than 56 bytes,
+//! - a length prefix with leading zero bytes,
+//! - trailing bytes after the top-level item.
+//!
+//! Integers are big-endian byte strings without leading zeros (zero is the
+//! empty string); [`Item::as_u64`] and [`Item::as_u256`] reject leading zeros.
+//! Transaction payloads built on this live in [`tx`].
+
+pub mod tx;
+
+use ethers::types::U256;
+
+pub use tx::{AccessListEntry, Eip1559Tx, Eip2930Tx, LegacyTx, SignedTx, TxPayload, TxSignature};
+
+/// Deepest list nesting accepted by [`Item::decode`]
+pub const MAX_DEPTH: usize = 64;
+
+const STRING_OFFSET: u8 = 0x80;
+const LIST_OFFSET: u8 = 0xc0;
+/// Payloads shorter than this use the single-byte header
+const SHORT_LIMIT: usize = 56;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RlpError {
+    #[error("Input ends inside an item")]
+    UnexpectedEnd,
+    #[error("{0} bytes follow the encoded item")]
+    TrailingBytes(usize),
+    #[error("Single byte below 0x80 is wrapped in a string header")]
+    NonCanonicalSingleByte,
+    #[error("Long-form header used for a {0}-byte payload")]
+    NonCanonicalLength(usize),
+    #[error("Length prefix has leading zero bytes")]
+    LeadingZeroLength,
+    #[error("Length prefix does not fit in memory")]
+    LengthOverflow,
+    #[error("Integer has leading zero bytes")]
+    LeadingZeroInteger,
+    #[error("Integer does not fit in {0} bits")]
+    IntegerOverflow(u32),
+    #[error("Expected a byte string, found a list")]
+    ExpectedBytes,
+    #[error("Expected a list, found a byte string")]
+    ExpectedList,
+    #[error("Expected {expected} bytes, found {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+    #[error("Expected a list of {expected} items, found {actual}")]
+    FieldCount { expected: usize, actual: usize },
+    #[error("Lists nested deeper than {0}")]
+    TooDeep(usize),
+    #[error("Unsupported transaction type 0x{0:02x}")]
+    UnknownTxType(u8),
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("Unsupported transaction: {0}")]
+    Unsupported(String),
+}
+
+/// A decoded RLP value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    Bytes(Vec<u8>),
+    List(Vec<Item>),
+}
+
+impl Item {
+    pub fn bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Item::Bytes(bytes.into())
+    }
+
+    pub fn u64(value: u64) -> Self {
+        Item::Bytes(trim_leading_zeros(&value.to_be_bytes()).to_vec())
+    }
+
+    pub fn u256(value: U256) -> Self {
+        let mut word = [0u8; 32];
+        value.to_big_endian(&mut word);
+        Item::Bytes(trim_leading_zeros(&word).to_vec())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_into(&mut out);
+        out
+    }
+
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        match self {
+            Item::Bytes(bytes) if bytes.len() == 1 && bytes[0] < STRING_OFFSET => out.push(bytes[0]),
+            Item::Bytes(bytes) => {
+                push_header(out, STRING_OFFSET, bytes.len());
+                out.extend_from_slice(bytes);
+            }
+            Item::List(items) => {
+                let mut payload = Vec::new();
+                for item in items {
+                    item.encode_into(&mut payload);
+                }
+                push_header(out, LIST_OFFSET, payload.len());
+                out.extend_from_slice(&payload);
+            }
+        }
+    }
+
+    /// Decodes exactly one canonical item spanning all of `input`.
+    pub fn decode(input: &[u8]) -> Result<Self, RlpError> {
+        let (item, used) = decode_item(input, 0)?;
+        match input.len() - used {
+            0 => Ok(item),
+            rest => Err(RlpError::TrailingBytes(rest)),
+        }
+    }
+
+    pub fn as_bytes(&self) -> Result<&[u8], RlpError> {
+        match self {
+            Item::Bytes(bytes) => Ok(bytes),
+            Item::List(_) => Err(RlpError::ExpectedBytes),
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&[Item], RlpError> {
+        match self {
+            Item::List(items) => Ok(items),
+            Item::Bytes(_) => Err(RlpError::ExpectedList),
+        }
+    }
+
+    pub fn as_u64(&self) -> Result<u64, RlpError> {
+        let bytes = integer_bytes(self, 8)?;
+        Ok(bytes.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b)))
+    }
+
+    pub fn as_u256(&self) -> Result<U256, RlpError> {
+        Ok(U256::from_big_endian(integer_bytes(self, 32)?))
+    }
+}
+
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+fn push_header(out: &mut Vec<u8>, offset: u8, len: usize) {
+    if len < SHORT_LIMIT {
+        out.push(offset + len as u8);
+    } else {
+        let be = len.to_be_bytes();
+        let len_bytes = trim_leading_zeros(&be);
+        out.push(offset + 55 + len_bytes.len() as u8);
+        out.extend_from_slice(len_bytes);
+    }
+}
+
+/// The minimal big-endian bytes of an integer item at most `width` bytes wide
+fn integer_bytes(item: &Item, width: usize) -> Result<&[u8], RlpError> {
+    let bytes = item.as_bytes()?;
+    if bytes.first() == Some(&0) {
+        return Err(RlpError::LeadingZeroInteger);
+    }
+    if bytes.len() > width {
+        return Err(RlpError::IntegerOverflow(width as u32 * 8));
+    }
+    Ok(bytes)
+}
+
+/// Header length and payload length of the item starting at `input[0]`
+fn read_header(input: &[u8], offset: u8) -> Result<(usize, usize), RlpError> {
+    let short = usize::from(input[0] - offset);
+    if short < SHORT_LIMIT {
+        return Ok((1, short));
+    }
+    let len_of_len = short - 55;
+    let len_bytes = input.get(1..1 + len_of_len).ok_or(RlpError::UnexpectedEnd)?;
+    if len_bytes[0] == 0 {
+        return Err(RlpError::LeadingZeroLength);
+    }
+    if len_of_len > std::mem::size_of::<usize>() {
+        return Err(RlpError::LengthOverflow);
+    }
+    let len = len_bytes.iter().fold(0usize, |acc, b| (acc << 8) | usize::from(*b));
+    if len < SHORT_LIMIT {
+        return Err(RlpError::NonCanonicalLength(len));
+    }
+    Ok((1 + len_of_len, len))
+}
+
+/// Decodes the item at the start of `input`; returns it with the bytes used.
+fn decode_item(input: &[u8], depth: usize) -> Result<(Item, usize), RlpError> {
+    let prefix = *input.first().ok_or(RlpError::UnexpectedEnd)?;
+    if prefix < STRING_OFFSET {
+        return Ok((Item::Bytes(vec![prefix]), 1));
+    }
+    let offset = if prefix < LIST_OFFSET { STRING_OFFSET } else { LIST_OFFSET };
+    let (header, len) = read_header(input, offset)?;
+    let end = header.checked_add(len).ok_or(RlpError::LengthOverflow)?;
+    let payload = input.get(header..end).ok_or(RlpError::UnexpectedEnd)?;
+
+    if offset == STRING_OFFSET {
+        if len == 1 && payload[0] < STRING_OFFSET {
+            return Err(RlpError::NonCanonicalSingleByte);
+        }
+        return Ok((Item::Bytes(payload.to_vec()), end));
+    }
+    if depth >= MAX_DEPTH {
+        return Err(RlpError::TooDeep(MAX_DEPTH));
+    }
+    let mut items = Vec::new();
+    let mut rest = payload;
+    while !rest.is_empty() {
+        let (item, used) = decode_item(rest, depth + 1)?;
+        items.push(item);
+        rest = &rest[used..];
+    }
+    Ok((Item::List(items), end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        hex::decode(text).unwrap()
+    }
+
+    #[test]
+    fn test_encodes_reference_values() {
+        let cases = [
+            (Item::bytes(b"dog".to_vec()), "83646f67"),
+            (Item::List(vec![Item::bytes(b"cat".to_vec()), Item::bytes(b"dog".to_vec())]), "c88363617483646f67"),
+            (Item::bytes(Vec::new()), "80"),
+            (Item::List(Vec::new()), "c0"),
+            (Item::u64(0), "80"),
+            (Item::u64(15), "0f"),
+            (Item::u64(1024), "820400"),
+            (Item::bytes(vec![0x80]), "8180"),
+            (
+                Item::List(vec![
+                    Item::List(Vec::new()),
+                    Item::List(vec![Item::List(Vec::new())]),
+                    Item::List(vec![Item::List(Vec::new()), Item::List(vec![Item::List(Vec::new())])]),
+                ]),
+                "c7c0c1c0c3c0c1c0",
+            ),
+        ];
+        for (item, encoded) in cases {
+            assert_eq!(hex::encode(item.encode()), encoded);
+            assert_eq!(Item::decode(&hex(encoded)).unwrap(), item);
+        }
+
+        let long = Item::bytes(b"Lorem ipsum dolor sit amet, consectetur adipisicing elit".to_vec());
+        assert_eq!(&long.encode()[..2], &[0xb8, 0x38]);
+    }
+
+    #[test]
+    fn test_rejects_non_canonical_forms() {
+        let cases = [
+            ("8105", RlpError::NonCanonicalSingleByte),
+            ("b80100", RlpError::NonCanonicalLength(1)),
+            ("f801c0", RlpError::NonCanonicalLength(1)),
+            ("b9000100", RlpError::LeadingZeroLength),
+            ("83646f", RlpError::UnexpectedEnd),
+            ("c28364", RlpError::UnexpectedEnd),
+            ("8000", RlpError::TrailingBytes(1)),
+            ("", RlpError::UnexpectedEnd),
+        ];
+        for (encoded, expected) in cases {
+            assert_eq!(Item::decode(&hex(encoded)), Err(expected), "{}", encoded);
+        }
+    }
+
+    #[test]
+    fn test_integers_must_be_minimal() {
+        assert_eq!(Item::decode(&hex("820400")).unwrap().as_u64(), Ok(1024));
+        assert_eq!(Item::decode(&hex("820004")).unwrap().as_u64(), Err(RlpError::LeadingZeroInteger));
+        assert_eq!(Item::decode(&hex("00")).unwrap().as_u64(), Err(RlpError::LeadingZeroInteger));
+        assert_eq!(Item::bytes(vec![1; 9]).as_u64(), Err(RlpError::IntegerOverflow(64)));
+        assert_eq!(Item::u256(U256::MAX).as_u256(), Ok(U256::MAX));
+        assert_eq!(Item::List(Vec::new()).as_u64(), Err(RlpError::ExpectedBytes));
+    }
+
+    #[test]
+    fn test_nesting_is_bounded() {
+        let mut encoded = vec![0xc0];
+        for _ in 0..MAX_DEPTH {
+            let mut outer = Vec::new();
+            push_header(&mut outer, LIST_OFFSET, encoded.len());
+            outer.extend_from_slice(&encoded);
+            encoded = outer;
+        }
+        assert_eq!(Item::decode(&encoded), Err(RlpError::TooDeep(MAX_DEPTH)));
+    }
+}
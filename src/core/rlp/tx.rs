@@ -0,0 +1,543 @@
+//! Transaction payloads on top of [`Item`].
+//!
+//! - legacy: signs `rlp([nonce, gasPrice, gas, to, value, data, chainId, 0, 0])`
+//!   (EIP-155) or the first six fields alone, and is sent as
+//!   `rlp([.. six fields, v, r, s])`;
+//! - EIP-2930 (`0x01`): signs `0x01 ‖ rlp([chainId, nonce, gasPrice, gas, to,
+//!   value, data, accessList])`, sent with `yParity, r, s` appended to the list;
+//! - EIP-1559 (`0x02`): the same shape with `maxPriorityFeePerGas,
+//!   maxFeePerGas` in place of `gasPrice`.
+//!
+//! A legacy `v` is `27 + parity`, or `35 + 2 * chainId + parity` with EIP-155;
+//! a decoded legacy transaction takes its chain id from `v`. Typed
+//! transactions carry the bare parity.
+//!
+//! [`sign_transaction`] is the signing entry point shared by the Ethereum
+//! signing paths; `security.tx_encoding` picks between this module and the
+//! ethers encoder.
+
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip2930::AccessList;
+use ethers::types::{Address, Bytes, NameOrAddress, Signature, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use super::{Item, RlpError};
+use crate::core::config::TxEncoding;
+use crate::core::errors::WalletError;
+
+const EIP2930_TYPE: u8 = 0x01;
+const EIP1559_TYPE: u8 = 0x02;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessListEntry {
+    pub address: Address,
+    pub storage_keys: Vec<H256>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LegacyTx {
+    /// `None`: signed without replay protection
+    pub chain_id: Option<u64>,
+    pub nonce: U256,
+    pub gas_price: U256,
+    pub gas: U256,
+    /// `None` creates a contract
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Eip2930Tx {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub gas_price: U256,
+    pub gas: U256,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub access_list: Vec<AccessListEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Eip1559Tx {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas: U256,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub access_list: Vec<AccessListEntry>,
+}
+
+/// An unsigned transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxPayload {
+    Legacy(LegacyTx),
+    Eip2930(Eip2930Tx),
+    Eip1559(Eip1559Tx),
+}
+
+/// Signature components as they appear on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxSignature {
+    pub v: u64,
+    pub r: U256,
+    pub s: U256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedTx {
+    pub payload: TxPayload,
+    pub signature: TxSignature,
+}
+
+fn keccak(bytes: &[u8]) -> H256 {
+    H256::from_slice(&Keccak256::digest(bytes))
+}
+
+fn to_item(to: &Option<Address>) -> Item {
+    match to {
+        Some(address) => Item::bytes(address.as_bytes()),
+        None => Item::bytes(Vec::new()),
+    }
+}
+
+fn access_list_item(entries: &[AccessListEntry]) -> Item {
+    Item::List(
+        entries
+            .iter()
+            .map(|entry| {
+                let keys = entry.storage_keys.iter().map(|key| Item::bytes(key.as_bytes())).collect();
+                Item::List(vec![Item::bytes(entry.address.as_bytes()), Item::List(keys)])
+            })
+            .collect(),
+    )
+}
+
+fn fixed<const N: usize>(item: &Item) -> Result<[u8; N], RlpError> {
+    let bytes = item.as_bytes()?;
+    bytes.try_into().map_err(|_| RlpError::InvalidLength { expected: N, actual: bytes.len() })
+}
+
+fn decode_to(item: &Item) -> Result<Option<Address>, RlpError> {
+    if item.as_bytes()?.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Address::from(fixed::<20>(item)?)))
+}
+
+fn decode_access_list(item: &Item) -> Result<Vec<AccessListEntry>, RlpError> {
+    item.as_list()?
+        .iter()
+        .map(|entry| -> Result<AccessListEntry, RlpError> {
+            let [address, keys] = fields::<2>(entry)?;
+            Ok(AccessListEntry {
+                address: Address::from(fixed::<20>(address)?),
+                storage_keys: keys
+                    .as_list()?
+                    .iter()
+                    .map(|key| fixed::<32>(key).map(H256::from))
+                    .collect::<Result<_, _>>()?,
+            })
+        })
+        .collect()
+}
+
+/// The items of a list that must hold exactly `N` of them
+fn fields<const N: usize>(item: &Item) -> Result<&[Item; N], RlpError> {
+    let items = item.as_list()?;
+    items.try_into().map_err(|_| RlpError::FieldCount { expected: N, actual: items.len() })
+}
+
+fn parity_of(v: u64) -> Result<u64, RlpError> {
+    match v {
+        0 | 1 => Ok(v),
+        _ => Err(RlpError::InvalidSignature(format!("y parity must be 0 or 1, got {}", v))),
+    }
+}
+
+impl TxPayload {
+    /// The EIP-2718 type byte; `None` for legacy
+    pub fn tx_type(&self) -> Option<u8> {
+        match self {
+            TxPayload::Legacy(_) => None,
+            TxPayload::Eip2930(_) => Some(EIP2930_TYPE),
+            TxPayload::Eip1559(_) => Some(EIP1559_TYPE),
+        }
+    }
+
+    pub fn chain_id(&self) -> Option<u64> {
+        match self {
+            TxPayload::Legacy(tx) => tx.chain_id,
+            TxPayload::Eip2930(tx) => Some(tx.chain_id),
+            TxPayload::Eip1559(tx) => Some(tx.chain_id),
+        }
+    }
+
+    /// Every field preceding the signature
+    fn fields(&self) -> Vec<Item> {
+        match self {
+            TxPayload::Legacy(tx) => vec![
+                Item::u256(tx.nonce),
+                Item::u256(tx.gas_price),
+                Item::u256(tx.gas),
+                to_item(&tx.to),
+                Item::u256(tx.value),
+                Item::bytes(tx.data.clone()),
+            ],
+            TxPayload::Eip2930(tx) => vec![
+                Item::u64(tx.chain_id),
+                Item::u256(tx.nonce),
+                Item::u256(tx.gas_price),
+                Item::u256(tx.gas),
+                to_item(&tx.to),
+                Item::u256(tx.value),
+                Item::bytes(tx.data.clone()),
+                access_list_item(&tx.access_list),
+            ],
+            TxPayload::Eip1559(tx) => vec![
+                Item::u64(tx.chain_id),
+                Item::u256(tx.nonce),
+                Item::u256(tx.max_priority_fee_per_gas),
+                Item::u256(tx.max_fee_per_gas),
+                Item::u256(tx.gas),
+                to_item(&tx.to),
+                Item::u256(tx.value),
+                Item::bytes(tx.data.clone()),
+                access_list_item(&tx.access_list),
+            ],
+        }
+    }
+
+    /// Prefixes the type byte, if any, to an encoded list.
+    fn envelope(&self, fields: Vec<Item>) -> Vec<u8> {
+        let body = Item::List(fields).encode();
+        match self.tx_type() {
+            Some(tx_type) => [&[tx_type][..], &body].concat(),
+            None => body,
+        }
+    }
+
+    /// The bytes whose hash is signed
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut fields = self.fields();
+        if let TxPayload::Legacy(LegacyTx { chain_id: Some(chain_id), .. }) = self {
+            fields.extend([Item::u64(*chain_id), Item::u64(0), Item::u64(0)]);
+        }
+        self.envelope(fields)
+    }
+
+    pub fn sighash(&self) -> H256 {
+        keccak(&self.signing_payload())
+    }
+
+    /// The recovery parity a wire `v` stands for; fails if `v` does not
+    /// belong to this transaction type and chain.
+    pub fn parity(&self, v: u64) -> Result<u64, RlpError> {
+        match self {
+            TxPayload::Legacy(LegacyTx { chain_id: None, .. }) => match v {
+                27 | 28 => Ok(v - 27),
+                _ => Err(RlpError::InvalidSignature(format!("legacy v = {} without a chain id", v))),
+            },
+            TxPayload::Legacy(LegacyTx { chain_id: Some(chain_id), .. }) => {
+                let base = eip155_base(*chain_id)?;
+                parity_of(v.checked_sub(base).ok_or_else(|| {
+                    RlpError::InvalidSignature(format!("v = {} does not carry chain id {}", v, chain_id))
+                })?)
+            }
+            _ => parity_of(v),
+        }
+    }
+
+    /// The wire `v` for a recovery parity
+    pub fn v(&self, parity: bool) -> Result<u64, RlpError> {
+        let parity = u64::from(parity);
+        match self {
+            TxPayload::Legacy(LegacyTx { chain_id: None, .. }) => Ok(27 + parity),
+            TxPayload::Legacy(LegacyTx { chain_id: Some(chain_id), .. }) => Ok(eip155_base(*chain_id)? + parity),
+            _ => Ok(parity),
+        }
+    }
+}
+
+/// `35 + 2 * chain_id`
+fn eip155_base(chain_id: u64) -> Result<u64, RlpError> {
+    chain_id
+        .checked_mul(2)
+        .and_then(|doubled| doubled.checked_add(35))
+        .ok_or_else(|| RlpError::InvalidSignature(format!("chain id {} is too large for EIP-155", chain_id)))
+}
+
+impl TxSignature {
+    /// Re-expresses a recoverable signature (`v` of 0/1, 27/28 or EIP-155
+    /// form) in the form `payload` puts on the wire.
+    pub fn for_payload(payload: &TxPayload, signature: &Signature) -> Result<Self, RlpError> {
+        let parity = match signature.v {
+            v @ (0 | 1) => v,
+            v @ (27 | 28) => v - 27,
+            // ethers signers hand back EIP-155 `v` for typed transactions too
+            v => match payload.chain_id() {
+                Some(chain_id) if payload.tx_type().is_some() => {
+                    parity_of(v.checked_sub(eip155_base(chain_id)?).ok_or_else(|| {
+                        RlpError::InvalidSignature(format!(
+                            "v = {} does not carry chain id {}",
+                            v, chain_id
+                        ))
+                    })?)?
+                }
+                _ => payload.parity(v)?,
+            },
+        };
+        Ok(Self { v: payload.v(parity == 1)?, r: signature.r, s: signature.s })
+    }
+}
+
+impl SignedTx {
+    pub fn encode(&self) -> Result<Vec<u8>, RlpError> {
+        self.payload.parity(self.signature.v)?;
+        let mut fields = self.payload.fields();
+        fields.extend([Item::u64(self.signature.v), Item::u256(self.signature.r), Item::u256(self.signature.s)]);
+        Ok(self.payload.envelope(fields))
+    }
+
+    pub fn decode(raw: &[u8]) -> Result<Self, RlpError> {
+        let (&first, rest) = raw.split_first().ok_or(RlpError::UnexpectedEnd)?;
+        match first {
+            EIP2930_TYPE => Self::decode_eip2930(&Item::decode(rest)?),
+            EIP1559_TYPE => Self::decode_eip1559(&Item::decode(rest)?),
+            0xc0.. => Self::decode_legacy(&Item::decode(raw)?),
+            other => Err(RlpError::UnknownTxType(other)),
+        }
+    }
+
+    /// The transaction hash
+    pub fn hash(&self) -> Result<H256, RlpError> {
+        Ok(keccak(&self.encode()?))
+    }
+
+    fn decode_legacy(item: &Item) -> Result<Self, RlpError> {
+        let [nonce, gas_price, gas, to, value, data, v, r, s] = fields::<9>(item)?;
+        let v = v.as_u64()?;
+        let chain_id = match v {
+            27 | 28 => None,
+            35.. => Some((v - 35) / 2),
+            _ => return Err(RlpError::InvalidSignature(format!("legacy v = {}", v))),
+        };
+        let payload = TxPayload::Legacy(LegacyTx {
+            chain_id,
+            nonce: nonce.as_u256()?,
+            gas_price: gas_price.as_u256()?,
+            gas: gas.as_u256()?,
+            to: decode_to(to)?,
+            value: value.as_u256()?,
+            data: data.as_bytes()?.to_vec(),
+        });
+        Ok(Self { payload, signature: TxSignature { v, r: r.as_u256()?, s: s.as_u256()? } })
+    }
+
+    fn decode_eip2930(item: &Item) -> Result<Self, RlpError> {
+        let [chain_id, nonce, gas_price, gas, to, value, data, access_list, y_parity, r, s] = fields::<11>(item)?;
+        let payload = TxPayload::Eip2930(Eip2930Tx {
+            chain_id: chain_id.as_u64()?,
+            nonce: nonce.as_u256()?,
+            gas_price: gas_price.as_u256()?,
+            gas: gas.as_u256()?,
+            to: decode_to(to)?,
+            value: value.as_u256()?,
+            data: data.as_bytes()?.to_vec(),
+            access_list: decode_access_list(access_list)?,
+        });
+        let v = parity_of(y_parity.as_u64()?)?;
+        Ok(Self { payload, signature: TxSignature { v, r: r.as_u256()?, s: s.as_u256()? } })
+    }
+
+    fn decode_eip1559(item: &Item) -> Result<Self, RlpError> {
+        let [chain_id, nonce, max_priority_fee, max_fee, gas, to, value, data, access_list, y_parity, r, s] =
+            fields::<12>(item)?;
+        let payload = TxPayload::Eip1559(Eip1559Tx {
+            chain_id: chain_id.as_u64()?,
+            nonce: nonce.as_u256()?,
+            max_priority_fee_per_gas: max_priority_fee.as_u256()?,
+            max_fee_per_gas: max_fee.as_u256()?,
+            gas: gas.as_u256()?,
+            to: decode_to(to)?,
+            value: value.as_u256()?,
+            data: data.as_bytes()?.to_vec(),
+            access_list: decode_access_list(access_list)?,
+        });
+        let v = parity_of(y_parity.as_u64()?)?;
+        Ok(Self { payload, signature: TxSignature { v, r: r.as_u256()?, s: s.as_u256()? } })
+    }
+}
+
+fn recipient(to: Option<&NameOrAddress>) -> Result<Option<Address>, RlpError> {
+    match to {
+        None => Ok(None),
+        Some(NameOrAddress::Address(address)) => Ok(Some(*address)),
+        Some(NameOrAddress::Name(name)) => Err(RlpError::Unsupported(format!("unresolved ENS recipient {}", name))),
+    }
+}
+
+fn access_list(list: &AccessList) -> Vec<AccessListEntry> {
+    list.0
+        .iter()
+        .map(|item| AccessListEntry { address: item.address, storage_keys: item.storage_keys.clone() })
+        .collect()
+}
+
+/// Unset numeric fields encode as zero, as they do in ethers. Typed
+/// transactions must carry a chain id.
+impl TryFrom<&TypedTransaction> for TxPayload {
+    type Error = RlpError;
+
+    fn try_from(tx: &TypedTransaction) -> Result<Self, RlpError> {
+        let typed_chain_id = || {
+            tx.chain_id()
+                .map(|id| id.as_u64())
+                .ok_or_else(|| RlpError::Unsupported("typed transaction without a chain id".to_string()))
+        };
+        let data = tx.data().map(|d| d.to_vec()).unwrap_or_default();
+        Ok(match tx {
+            TypedTransaction::Legacy(req) => TxPayload::Legacy(LegacyTx {
+                chain_id: req.chain_id.map(|id| id.as_u64()),
+                nonce: req.nonce.unwrap_or_default(),
+                gas_price: req.gas_price.unwrap_or_default(),
+                gas: req.gas.unwrap_or_default(),
+                to: recipient(req.to.as_ref())?,
+                value: req.value.unwrap_or_default(),
+                data,
+            }),
+            TypedTransaction::Eip2930(req) => TxPayload::Eip2930(Eip2930Tx {
+                chain_id: typed_chain_id()?,
+                nonce: req.tx.nonce.unwrap_or_default(),
+                gas_price: req.tx.gas_price.unwrap_or_default(),
+                gas: req.tx.gas.unwrap_or_default(),
+                to: recipient(req.tx.to.as_ref())?,
+                value: req.tx.value.unwrap_or_default(),
+                data,
+                access_list: access_list(&req.access_list),
+            }),
+            TypedTransaction::Eip1559(req) => TxPayload::Eip1559(Eip1559Tx {
+                chain_id: typed_chain_id()?,
+                nonce: req.nonce.unwrap_or_default(),
+                max_priority_fee_per_gas: req.max_priority_fee_per_gas.unwrap_or_default(),
+                max_fee_per_gas: req.max_fee_per_gas.unwrap_or_default(),
+                gas: req.gas.unwrap_or_default(),
+                to: recipient(req.to.as_ref())?,
+                value: req.value.unwrap_or_default(),
+                data,
+                access_list: access_list(&req.access_list),
+            }),
+            #[allow(unreachable_patterns)]
+            _ => return Err(RlpError::Unsupported("transaction type".to_string())),
+        })
+    }
+}
+
+fn signing_failed(e: impl std::fmt::Display) -> WalletError {
+    WalletError::CryptoError(format!("Failed to sign transaction: {}", e))
+}
+
+/// Signs `tx` and returns the raw signed transaction. A missing chain id is
+/// taken from `signer`, as ethers does.
+pub fn sign_transaction(
+    encoding: TxEncoding,
+    signer: &LocalWallet,
+    tx: &TypedTransaction,
+) -> Result<Bytes, WalletError> {
+    match encoding {
+        TxEncoding::Ethers => {
+            let signature = signer.sign_transaction_sync(tx).map_err(signing_failed)?;
+            Ok(tx.rlp_signed(&signature))
+        }
+        TxEncoding::Native => {
+            let mut tx = tx.clone();
+            if tx.chain_id().is_none() {
+                tx.set_chain_id(signer.chain_id());
+            }
+            let payload = TxPayload::try_from(&tx).map_err(signing_failed)?;
+            let signature = signer.sign_hash(payload.sighash()).map_err(signing_failed)?;
+            let signature = TxSignature::for_payload(&payload, &signature).map_err(signing_failed)?;
+            let raw = SignedTx { payload, signature }.encode().map_err(signing_failed)?;
+            Ok(raw.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy(chain_id: Option<u64>) -> TxPayload {
+        TxPayload::Legacy(LegacyTx { chain_id, nonce: 9.into(), gas: 21_000.into(), ..Default::default() })
+    }
+
+    #[test]
+    fn test_v_follows_the_transaction_type() {
+        assert_eq!(legacy(None).v(true), Ok(28));
+        assert_eq!(legacy(Some(1)).v(false), Ok(37));
+        assert_eq!(legacy(Some(1)).parity(38), Ok(1));
+        assert!(legacy(Some(1)).parity(27).is_err());
+        assert!(legacy(Some(u64::MAX)).v(false).is_err());
+
+        let typed = TxPayload::Eip1559(Eip1559Tx { chain_id: 1, ..Default::default() });
+        assert_eq!(typed.v(true), Ok(1));
+        assert!(typed.parity(27).is_err());
+
+        let signature = Signature { r: 1.into(), s: 2.into(), v: 28 };
+        assert_eq!(TxSignature::for_payload(&legacy(Some(5)), &signature).unwrap().v, 46);
+        assert_eq!(TxSignature::for_payload(&typed, &signature).unwrap().v, 1);
+        let eip155 = Signature { v: 38, ..signature };
+        assert_eq!(TxSignature::for_payload(&typed, &eip155).unwrap().v, 1);
+        assert!(TxSignature::for_payload(&typed, &Signature { v: 46, ..signature }).is_err());
+    }
+
+    #[test]
+    fn test_eip155_reference_transaction() {
+        // EIP-155 example: nonce 9, 20 gwei, 21000 gas, 1 ether to 0x3535..35 on chain 1
+        let payload = TxPayload::Legacy(LegacyTx {
+            chain_id: Some(1),
+            nonce: 9.into(),
+            gas_price: U256::from(20_000_000_000u64),
+            gas: 21_000.into(),
+            to: Some(Address::repeat_byte(0x35)),
+            value: U256::exp10(18),
+            data: Vec::new(),
+        });
+        assert_eq!(
+            hex::encode(payload.signing_payload()),
+            "ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080"
+        );
+        assert_eq!(
+            format!("{:x}", payload.sighash()),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+
+        let signed = SignedTx {
+            payload,
+            signature: TxSignature {
+                v: 37,
+                r: U256::from_dec_str(
+                    "18515461264373351373200002665853028612451056578545711640558177340181847433846",
+                )
+                .unwrap(),
+                s: U256::from_dec_str(
+                    "46948507304638947509940763649030358759909902576025900602547168820602576006531",
+                )
+                .unwrap(),
+            },
+        };
+        let raw = signed.encode().unwrap();
+        assert_eq!(
+            hex::encode(&raw),
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a0\
+             28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9\
+             f3dc64214b297fb1966a3b6d83"
+        );
+        assert_eq!(SignedTx::decode(&raw).unwrap(), signed);
+    }
+}
@@ -1,8 +1,13 @@
 use anyhow::Result;
 use regex::Regex;
+use serde::Serialize;
 // keep a single Regex import; used in multiple validators
 use sha3::{Digest, Keccak256};
 
+use crate::core::amount::{Amount, AssetTag};
+
+pub mod eip681;
+
 /// Validates an Ethereum address.
 pub fn validate_ethereum_address(address: &str) -> Result<()> {
     if !address.starts_with("0x") || address.len() != 42 {
@@ -38,16 +43,8 @@ fn is_eip55_checksum_valid(addr: &str) -> bool {
     for (i, ch) in body.chars().enumerate() {
         let nibble = (hash[i / 2] >> (4 * (1 - (i % 2)))) & 0x0f;
         match ch {
-            'a'..='f' => {
-                if nibble >= 8 {
-                    return false;
-                }
-            }
-            'A'..='F' => {
-                if nibble < 8 {
-                    return false;
-                }
-            }
+            'a'..='f' if nibble >= 8 => return false,
+            'A'..='F' if nibble < 8 => return false,
             _ => {}
         }
     }
@@ -79,13 +76,143 @@ pub fn validate_address(address: &str, network: &str) -> Result<()> {
     }
 }
 
-/// Validates an amount string (positive number).
+/// Longest input [`detect_address`] looks at; longer strings are no address
+/// on any supported chain.
+pub const MAX_ADDRESS_INPUT_LEN: usize = 128;
+
+/// EVM networks sharing one address format
+const EVM_NETWORKS: &[&str] = &["eth", "sepolia", "polygon", "polygon-testnet", "bsc", "bsctestnet"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainFamily {
+    Evm,
+    Bitcoin,
+}
+
+/// One reading of a pasted address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddressMatch {
+    pub family: ChainFamily,
+    /// `evm`, or the Bitcoin script type (`p2pkh`, `p2sh`, `p2wpkh`, `p2wsh`, `p2tr`)
+    pub format: String,
+    /// Bitcoin: `mainnet`, `testnet` (also signet) or `regtest`; EVM addresses are chain-independent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<&'static str>,
+    /// Networks of this service the address can be used on
+    pub networks: Vec<&'static str>,
+    /// EIP-55 checksummed (EVM) or lowercase bech32 form
+    pub normalized: String,
+    /// EVM only: `valid`, `invalid` or `absent` (single-case hex)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<&'static str>,
+    pub warnings: Vec<String>,
+}
+
+impl AddressMatch {
+    /// "an EVM address", "a Bitcoin testnet address", ...
+    pub fn describe(&self) -> String {
+        match (self.family, self.network) {
+            (ChainFamily::Evm, _) => "an EVM address".to_string(),
+            (ChainFamily::Bitcoin, Some("mainnet") | None) => "a Bitcoin address".to_string(),
+            (ChainFamily::Bitcoin, Some(network)) => format!("a Bitcoin {} address", network),
+        }
+    }
+}
+
+/// Recognizes `input` as an address of every supported chain family it could
+/// belong to. Empty when it is none; never panics, whatever the input.
+///
+/// Solana keys are not recognized: there is no Solana client to send with.
+pub fn detect_address(input: &str) -> Vec<AddressMatch> {
+    let input = input.trim();
+    if input.is_empty() || input.len() > MAX_ADDRESS_INPUT_LEN {
+        return Vec::new();
+    }
+    detect_evm(input).into_iter().chain(detect_bitcoin(input)).collect()
+}
+
+fn detect_evm(input: &str) -> Option<AddressMatch> {
+    let hex = input.strip_prefix("0x")?;
+    if hex.len() != 40 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let address: ethers::types::Address = input.parse().ok()?;
+    let normalized = ethers::utils::to_checksum(&address, None);
+    let mixed_case = hex.bytes().any(|b| b.is_ascii_lowercase()) && hex.bytes().any(|b| b.is_ascii_uppercase());
+    let mut warnings = Vec::new();
+    let checksum = if !mixed_case {
+        "absent"
+    } else if normalized == input {
+        "valid"
+    } else {
+        warnings.push(format!("EIP-55 checksum does not match; the checksummed form is {}", normalized));
+        "invalid"
+    };
+    if address.is_zero() {
+        warnings.push("This is the zero address; funds sent to it cannot be recovered".to_string());
+    }
+    Some(AddressMatch {
+        family: ChainFamily::Evm,
+        format: "evm".to_string(),
+        network: None,
+        networks: EVM_NETWORKS.to_vec(),
+        normalized,
+        checksum: Some(checksum),
+        warnings,
+    })
+}
+
+#[cfg(feature = "bitcoin")]
+fn detect_bitcoin(input: &str) -> Option<AddressMatch> {
+    use bitcoin::address::NetworkUnchecked;
+    use bitcoin::{Address, Network};
+    use std::str::FromStr;
+
+    let address = Address::<NetworkUnchecked>::from_str(input).ok()?;
+    // 测试网 base58 前缀同时对 regtest 有效，先判 testnet
+    let network = if address.is_valid_for_network(Network::Bitcoin) {
+        "mainnet"
+    } else if address.is_valid_for_network(Network::Testnet) {
+        "testnet"
+    } else if address.is_valid_for_network(Network::Regtest) {
+        "regtest"
+    } else {
+        return None;
+    };
+    let address = address.assume_checked();
+    let format = address.address_type().map(|t| t.to_string()).unwrap_or_else(|| "unknown".to_string());
+    let mut warnings = Vec::new();
+    if network != "mainnet" {
+        warnings.push(format!("This is a Bitcoin {} address; coins on it have no real value", network));
+    }
+    Some(AddressMatch {
+        family: ChainFamily::Bitcoin,
+        format,
+        network: Some(network),
+        networks: if network == "mainnet" { vec!["btc"] } else { Vec::new() },
+        normalized: address.to_string(),
+        checksum: None,
+        warnings,
+    })
+}
+
+#[cfg(not(feature = "bitcoin"))]
+fn detect_bitcoin(_input: &str) -> Option<AddressMatch> {
+    None
+}
+
+/// Validates an amount string (positive number, up to 18 decimals).
+///
+/// The string is parsed exactly; the returned `f64` is an approximation for
+/// display. Use [`Amount::parse`] when the value feeds arithmetic.
 pub fn validate_amount(amount: &str) -> Result<f64> {
-    let amount: f64 = amount.parse().map_err(|_| anyhow::anyhow!("Invalid amount format"))?;
-    if amount <= 0.0 {
+    let amount = Amount::parse(amount, 18, AssetTag::new("", ""))
+        .map_err(|_| anyhow::anyhow!("Invalid amount format"))?;
+    if amount.is_zero() {
         return Err(anyhow::anyhow!("Amount must be positive"));
     }
-    Ok(amount)
+    Ok(amount.to_f64_lossy())
 }
 
 /// Strict decimal validator for amounts to avoid float parsing where exactness matters.
@@ -198,7 +325,101 @@ mod tests {
         assert!(validate_amount_strict(".1", 18).is_err());
     }
 
+    /// (input, format, network, normalized, warning count)
+    #[cfg(feature = "bitcoin")]
+    const BITCOIN_VECTORS: &[(&str, &str, &str, &str, usize)] = &[
+        ("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2", "p2pkh", "mainnet", "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2", 0),
+        ("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy", "p2sh", "mainnet", "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy", 0),
+        // BIP-173 全大写 bech32
+        (
+            "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+            "p2wpkh",
+            "mainnet",
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            0,
+        ),
+        (
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+            "p2wsh",
+            "testnet",
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+            1,
+        ),
+        // BIP-350 bech32m
+        (
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+            "p2tr",
+            "mainnet",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+            0,
+        ),
+    ];
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn test_detect_bitcoin_addresses() {
+        for (input, format, network, normalized, warnings) in BITCOIN_VECTORS {
+            let matches = detect_address(input);
+            assert_eq!(matches.len(), 1, "{}", input);
+            let m = &matches[0];
+            assert_eq!(m.family, ChainFamily::Bitcoin, "{}", input);
+            assert_eq!(m.format, *format, "{}", input);
+            assert_eq!(m.network, Some(*network), "{}", input);
+            assert_eq!(m.normalized, *normalized, "{}", input);
+            assert_eq!(m.warnings.len(), *warnings, "{}", input);
+            assert_eq!(m.networks.is_empty(), *network != "mainnet", "{}", input);
+        }
+        assert_eq!(detect_address(BITCOIN_VECTORS[3].0)[0].describe(), "a Bitcoin testnet address");
+        // 大小写混合的 bech32 与校验和错误都不是address
+        assert!(detect_address("bc1qW508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4").is_empty());
+        assert!(detect_address("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3").is_empty());
+    }
+
+    #[test]
+    fn test_detect_evm_checksum_status() {
+        let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let cases = [
+            (checksummed, "valid", 0),
+            ("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "absent", 0),
+            ("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED", "absent", 0),
+            ("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD", "invalid", 1),
+            ("0x0000000000000000000000000000000000000000", "absent", 1),
+        ];
+        for (input, checksum, warnings) in cases {
+            let matches = detect_address(input);
+            assert_eq!(matches.len(), 1, "{}", input);
+            assert_eq!(matches[0].family, ChainFamily::Evm);
+            assert_eq!(matches[0].checksum, Some(checksum), "{}", input);
+            assert_eq!(matches[0].warnings.len(), warnings, "{}", input);
+            assert!(matches[0].networks.contains(&"eth"));
+        }
+        assert_eq!(detect_address(&format!("  {}\n", checksummed.to_lowercase()))[0].normalized, checksummed);
+    }
+
+    #[test]
+    fn test_detect_rejects_non_addresses() {
+        for input in ["", "   ", "0x", "0xabc", "hello world", "0X5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"] {
+            assert!(detect_address(input).is_empty(), "{:?}", input);
+        }
+        assert!(detect_address(&"1".repeat(1 << 20)).is_empty());
+        assert!(detect_address(&format!("bc1{}", "q".repeat(MAX_ADDRESS_INPUT_LEN))).is_empty());
+    }
+
     proptest! {
+        #[test]
+        fn prop_detect_never_panics(s in any::<String>()) {
+            let _ = detect_address(&s);
+        }
+
+        #[test]
+        fn prop_detect_never_panics_on_address_like_input(
+            s in proptest::string::string_regex(r"(0x|bc1[pq]?|tb1|bcrt1|BC1|[123mn2])[0-9A-Za-z]{0,100}").unwrap()
+        ) {
+            for m in detect_address(&s) {
+                prop_assert!(!m.normalized.is_empty());
+            }
+        }
+
         // Fuzz valid patterns up to 18 decimals using a single regex
         #[test]
         fn prop_valid_amounts_no_exponent(
@@ -0,0 +1,377 @@
+//! EIP-681 payment request URIs.
+//!
+//! `ethereum:[pay-]<address>[@<chain id>][/transfer][?<parameters>]`
+//!
+//! Two forms are understood: a native payment whose amount is `value`, and an
+//! ERC-20 `transfer` call whose target is the token contract and whose
+//! `address` / `uint256` parameters are the recipient and the amount in token
+//! base units. Numbers may use the URI's scientific notation (`2.014e18`) but
+//! must denote a non-negative integer. ENS names are refused: nothing here
+//! resolves them, and a payment must not depend on a lookup the operator never
+//! saw.
+
+use std::fmt;
+use std::str::FromStr;
+
+use ethers::types::{Address, U256};
+use ethers::utils::to_checksum;
+
+use super::validate_ethereum_address;
+
+pub const SCHEME: &str = "ethereum";
+
+/// Longest URI accepted; real payment links are well under 300 bytes
+pub const MAX_URI_LEN: usize = 2048;
+
+/// Digits in the largest `uint256` (2^256 - 1 has 78)
+const MAX_NUMBER_DIGITS: usize = 78;
+
+/// Gas hints a wallet may attach; accepted and dropped, gas is ours to price
+const GAS_PARAMETERS: &[&str] = &["gas", "gasLimit", "gasPrice"];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Eip681Error {
+    #[error("Payment URI is too long")]
+    TooLong,
+    #[error("Payment URI must start with ethereum:")]
+    Scheme,
+    #[error("ENS names are not supported in payment URIs; use a 0x address")]
+    EnsName,
+    #[error("Invalid {0} address in payment URI")]
+    Address(&'static str),
+    #[error("Invalid chain id in payment URI")]
+    ChainId,
+    #[error("Unsupported function {0:?} in payment URI (only transfer)")]
+    Function(String),
+    #[error("Invalid number for {0} in payment URI")]
+    Number(String),
+    #[error("Unsupported parameter {0:?} in payment URI")]
+    Parameter(String),
+    #[error("Parameter {0} appears more than once in payment URI")]
+    Duplicate(String),
+    #[error("transfer payment URI is missing the address parameter")]
+    MissingRecipient,
+}
+
+/// What a payment URI asks for, with every field normalized
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentIntent {
+    /// Account receiving the funds; for ERC-20 the `address` parameter, not the contract
+    pub recipient: Address,
+    /// `None` leaves the network to the payer
+    pub chain_id: Option<u64>,
+    /// ERC-20 contract; `None` for the chain's native coin
+    pub token: Option<Address>,
+    /// Minimal units (wei or token base units); `None` leaves the amount to the payer
+    pub amount: Option<U256>,
+}
+
+impl PaymentIntent {
+    pub fn native(recipient: Address, chain_id: Option<u64>, amount: Option<U256>) -> Self {
+        Self { recipient, chain_id, token: None, amount }
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.token.is_none()
+    }
+
+    /// Canonical form: checksummed addresses, integer amounts, no gas hints.
+    pub fn to_uri(&self) -> String {
+        let chain = self.chain_id.map(|id| format!("@{}", id)).unwrap_or_default();
+        match self.token {
+            None => {
+                let mut uri = format!("{}:{}{}", SCHEME, to_checksum(&self.recipient, None), chain);
+                if let Some(amount) = self.amount {
+                    uri.push_str(&format!("?value={}", amount));
+                }
+                uri
+            }
+            Some(token) => {
+                let mut uri = format!(
+                    "{}:{}{}/transfer?address={}",
+                    SCHEME,
+                    to_checksum(&token, None),
+                    chain,
+                    to_checksum(&self.recipient, None)
+                );
+                if let Some(amount) = self.amount {
+                    uri.push_str(&format!("&uint256={}", amount));
+                }
+                uri
+            }
+        }
+    }
+}
+
+impl fmt::Display for PaymentIntent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_uri())
+    }
+}
+
+impl FromStr for PaymentIntent {
+    type Err = Eip681Error;
+
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        parse_payment_uri(uri)
+    }
+}
+
+pub fn parse_payment_uri(uri: &str) -> Result<PaymentIntent, Eip681Error> {
+    let uri = uri.trim();
+    if uri.len() > MAX_URI_LEN {
+        return Err(Eip681Error::TooLong);
+    }
+    let rest = match uri.split_once(':') {
+        Some((scheme, rest)) if scheme.eq_ignore_ascii_case(SCHEME) => rest,
+        _ => return Err(Eip681Error::Scheme),
+    };
+    let rest = rest.strip_prefix("pay-").unwrap_or(rest);
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (target, function) = match path.split_once('/') {
+        Some((target, function)) => (target, Some(function)),
+        None => (path, None),
+    };
+    let (target, chain_id) = match target.split_once('@') {
+        Some((target, chain)) => (target, Some(parse_chain_id(chain)?)),
+        None => (target, None),
+    };
+    let target = parse_address(target, "target")?;
+
+    let params = parse_params(query)?;
+    match function {
+        None => {
+            let mut amount = None;
+            for (key, value) in params {
+                match key {
+                    "value" => amount = Some(parse_number(key, value)?),
+                    _ if GAS_PARAMETERS.contains(&key) => {
+                        parse_number(key, value)?;
+                    }
+                    _ => return Err(Eip681Error::Parameter(key.to_string())),
+                }
+            }
+            Ok(PaymentIntent::native(target, chain_id, amount))
+        }
+        Some("transfer") => {
+            let (mut recipient, mut amount) = (None, None);
+            for (key, value) in params {
+                match key {
+                    "address" => recipient = Some(parse_address(value, "recipient")?),
+                    "uint256" => amount = Some(parse_number(key, value)?),
+                    _ if GAS_PARAMETERS.contains(&key) => {
+                        parse_number(key, value)?;
+                    }
+                    // `value` would send ether to the token contract alongside the call
+                    _ => return Err(Eip681Error::Parameter(key.to_string())),
+                }
+            }
+            Ok(PaymentIntent {
+                recipient: recipient.ok_or(Eip681Error::MissingRecipient)?,
+                chain_id,
+                token: Some(target),
+                amount,
+            })
+        }
+        Some(other) => Err(Eip681Error::Function(other.to_string())),
+    }
+}
+
+fn parse_address(value: &str, role: &'static str) -> Result<Address, Eip681Error> {
+    if !value.starts_with("0x") && value.contains('.') {
+        return Err(Eip681Error::EnsName);
+    }
+    validate_ethereum_address(value).map_err(|_| Eip681Error::Address(role))?;
+    value.parse().map_err(|_| Eip681Error::Address(role))
+}
+
+fn parse_chain_id(value: &str) -> Result<u64, Eip681Error> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Eip681Error::ChainId);
+    }
+    match value.parse::<u64>() {
+        Ok(0) | Err(_) => Err(Eip681Error::ChainId),
+        Ok(id) => Ok(id),
+    }
+}
+
+fn parse_params(query: &str) -> Result<Vec<(&str, &str)>, Eip681Error> {
+    let mut params: Vec<(&str, &str)> = Vec::new();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').ok_or_else(|| Eip681Error::Parameter(pair.to_string()))?;
+        if params.iter().any(|(seen, _)| *seen == key) {
+            return Err(Eip681Error::Duplicate(key.to_string()));
+        }
+        params.push((key, value));
+    }
+    Ok(params)
+}
+
+/// EIP-681 `number`: `[+]digits[.digits][e digits]`, which must come out as
+/// an integer that fits in `uint256`.
+fn parse_number(key: &str, value: &str) -> Result<U256, Eip681Error> {
+    let invalid = || Eip681Error::Number(key.to_string());
+    let value = value.strip_prefix('+').unwrap_or(value);
+    let (mantissa, exponent) = match value.find(['e', 'E']) {
+        Some(at) => (&value[..at], Some(&value[at + 1..])),
+        None => (value, None),
+    };
+    let exponent: usize = match exponent {
+        Some(e) if !e.is_empty() && e.bytes().all(|b| b.is_ascii_digit()) && e.len() <= 3 => {
+            e.parse().map_err(|_| invalid())?
+        }
+        Some(_) => return Err(invalid()),
+        None => 0,
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(int) || !digits(frac) || (int.is_empty() && frac.is_empty()) || mantissa.ends_with('.') {
+        return Err(invalid());
+    }
+    let frac = frac.trim_end_matches('0');
+    // 1.5e0 is not a whole number of wei
+    let scale = exponent.checked_sub(frac.len()).ok_or_else(invalid)?;
+    let significant = format!("{}{}", int, frac);
+    let significant = significant.trim_start_matches('0');
+    if significant.is_empty() {
+        return Ok(U256::zero());
+    }
+    if significant.len() + scale > MAX_NUMBER_DIGITS {
+        return Err(invalid());
+    }
+    let base = U256::from_dec_str(significant).map_err(|_| invalid())?;
+    base.checked_mul(U256::exp10(scale)).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const TOKEN: &str = "0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7";
+    const RECIPIENT: &str = "0x8e23ee67d1332ad560396262c48ffbb01f93d052";
+
+    fn addr(s: &str) -> Address {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_native_value_notations() {
+        let cases = [
+            ("2.014e18", "2014000000000000000"),
+            ("1e18", "1000000000000000000"),
+            ("1E3", "1000"),
+            ("+5", "5"),
+            ("1000", "1000"),
+            ("0.5e1", "5"),
+            (".25e2", "25"),
+            ("1.500e3", "1500"),
+            ("0", "0"),
+        ];
+        for (value, wei) in cases {
+            let intent = parse_payment_uri(&format!("ethereum:{}?value={}", RECIPIENT, value)).unwrap();
+            assert_eq!(intent.amount, Some(U256::from_dec_str(wei).unwrap()), "{}", value);
+            assert!(intent.is_native());
+            assert_eq!(intent.chain_id, None);
+        }
+        for bad in ["1.5e0", "1e", "-1", "1.", "e18", "0x10", "1e79", "1,000", ""] {
+            assert_eq!(
+                parse_payment_uri(&format!("ethereum:{}?value={}", RECIPIENT, bad)),
+                Err(Eip681Error::Number("value".to_string())),
+                "{:?}",
+                bad
+            );
+        }
+        // 2^256 - 1 fits, 2^256 does not
+        let max = U256::MAX.to_string();
+        assert_eq!(parse_payment_uri(&format!("ethereum:{}?value={}", RECIPIENT, max)).unwrap().amount, Some(U256::MAX));
+        let over = "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+        assert!(parse_payment_uri(&format!("ethereum:{}?value={}", RECIPIENT, over)).is_err());
+    }
+
+    #[test]
+    fn test_chain_id_pay_prefix_and_gas_hints() {
+        let intent =
+            parse_payment_uri(&format!("ethereum:pay-{}@137?value=1e16&gasLimit=21000&gasPrice=3e10", RECIPIENT))
+                .unwrap();
+        assert_eq!(intent, PaymentIntent::native(addr(RECIPIENT), Some(137), Some(U256::exp10(16))));
+        assert_eq!(parse_payment_uri(&format!("ETHEREUM:{}", RECIPIENT)).unwrap().amount, None);
+
+        for bad in ["@", "@0", "@x1", "@-1", "@99999999999999999999999"] {
+            assert_eq!(parse_payment_uri(&format!("ethereum:{}{}", RECIPIENT, bad)), Err(Eip681Error::ChainId));
+        }
+    }
+
+    #[test]
+    fn test_erc20_transfer() {
+        let uri = format!("ethereum:{}@1/transfer?address={}&uint256=1e6", TOKEN, RECIPIENT);
+        let intent = parse_payment_uri(&uri).unwrap();
+        assert_eq!(intent.token, Some(addr(TOKEN)));
+        assert_eq!(intent.recipient, addr(RECIPIENT));
+        assert_eq!(intent.amount, Some(U256::from(1_000_000u64)));
+        assert_eq!(intent.chain_id, Some(1));
+        assert!(!intent.is_native());
+
+        assert_eq!(
+            parse_payment_uri(&format!("ethereum:{}/transfer?uint256=1", TOKEN)),
+            Err(Eip681Error::MissingRecipient)
+        );
+        assert_eq!(
+            parse_payment_uri(&format!("ethereum:{}/transfer?address={}&value=1", TOKEN, RECIPIENT)),
+            Err(Eip681Error::Parameter("value".to_string()))
+        );
+        assert_eq!(
+            parse_payment_uri(&format!("ethereum:{}/approve?address={}", TOKEN, RECIPIENT)),
+            Err(Eip681Error::Function("approve".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_rejections() {
+        assert_eq!(parse_payment_uri(RECIPIENT), Err(Eip681Error::Scheme));
+        assert_eq!(parse_payment_uri(&format!("bitcoin:{}", RECIPIENT)), Err(Eip681Error::Scheme));
+        assert_eq!(parse_payment_uri("ethereum:alice.eth?value=1"), Err(Eip681Error::EnsName));
+        assert_eq!(parse_payment_uri("ethereum:0x1234?value=1"), Err(Eip681Error::Address("target")));
+        // mixed case with a wrong EIP-55 checksum
+        let checksummed = to_checksum(&addr(RECIPIENT), None);
+        let at = checksummed[2..].find(|c: char| c.is_ascii_alphabetic()).unwrap() + 2;
+        let mut flipped = checksummed.into_bytes();
+        flipped[at] ^= 0x20;
+        let flipped = String::from_utf8(flipped).unwrap();
+        assert_eq!(parse_payment_uri(&format!("ethereum:{}", flipped)), Err(Eip681Error::Address("target")));
+        assert_eq!(
+            parse_payment_uri(&format!("ethereum:{}?value=1&value=2", RECIPIENT)),
+            Err(Eip681Error::Duplicate("value".to_string()))
+        );
+        assert_eq!(
+            parse_payment_uri(&format!("ethereum:{}?amount=1", RECIPIENT)),
+            Err(Eip681Error::Parameter("amount".to_string()))
+        );
+        assert_eq!(parse_payment_uri(&"x".repeat(MAX_URI_LEN + 1)), Err(Eip681Error::TooLong));
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        let intent = parse_payment_uri(&format!("ethereum:pay-{}@1?value=1.5e18&gas=21000", RECIPIENT)).unwrap();
+        let expected = format!("ethereum:{}@1?value=1500000000000000000", to_checksum(&addr(RECIPIENT), None));
+        assert_eq!(intent.to_uri(), expected);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_generate_then_parse_round_trips(
+            recipient in any::<[u8; 20]>(),
+            token in proptest::option::of(any::<[u8; 20]>()),
+            chain_id in proptest::option::of(1u64..u64::MAX),
+            amount in proptest::option::of(any::<u128>()),
+        ) {
+            let intent = PaymentIntent {
+                recipient: Address::from(recipient),
+                chain_id,
+                token: token.map(Address::from),
+                amount: amount.map(U256::from),
+            };
+            prop_assert_eq!(parse_payment_uri(&intent.to_uri()).unwrap(), intent);
+        }
+    }
+}
@@ -91,6 +91,8 @@ pub async fn create_wallet(
         quantum_safe,
         multi_sig_threshold: 2,
         networks: vec!["eth".to_string(), "polygon".to_string()],
+        allowed_networks: None,
+        derivation_path: None,
     };
 
     // P0: Do not generate or store Shamir shares; avoid co-locating reconstruction material.
@@ -103,6 +105,8 @@ pub async fn create_wallet(
         nonce: Vec::new(),
         schema_version: crate::core::wallet_info::SecureWalletData::default_schema_version(),
         kek_id: std::env::var("WALLET_KEK_ID").ok(),
+        key_kind: crate::core::wallet_info::WalletKeyKind::Hd,
+        passphrase_protected: false,
     };
 
     // Store securely
@@ -155,6 +159,60 @@ pub async fn derive_master_key(mnemonic: &str) -> Result<SecretVec, WalletError>
     Ok(vec_to_secret(seed_bytes[..32].to_vec()))
 }
 
+/// Load KEK from WALLET_ENC_KEY (base64 32 bytes). In test-env, a deterministic key is set.
+/// Shared by everything sealed under the envelope KEK (wallet keys, sweep capabilities).
+pub(crate) fn load_envelope_kek() -> Result<[u8; 32], WalletError> {
+    let b64 = crate::security::env_manager::secret_backend::master_key_encoded()
+        .map_err(|e| WalletError::CryptoError(e.to_string()))?;
+    let b64_raw = b64.clone();
+    let mut raw = base64::engine::general_purpose::STANDARD
+        .decode(b64_raw.trim())
+        .map_err(|_| WalletError::CryptoError("WALLET_ENC_KEY must be base64(32)".into()))?;
+    if raw.len() != 32 {
+        raw.zeroize();
+        return Err(WalletError::CryptoError("WALLET_ENC_KEY must be 32 bytes".into()));
+    }
+    // Reject an all-zero WALLET_ENC_KEY unless the runtime test override
+    // `TEST_SKIP_DECRYPT=1` is set (used by integration tests via the
+    // test-only server constructor). This avoids relying on compile-time
+    // features which don't apply to integration-test builds.
+    if raw.iter().all(|&b| b == 0) {
+        // Recognize the deterministic test key used by test constructors
+        // and allow it in that case.
+        let running_under_test_harness = std::env::var("RUST_TEST_THREADS").is_ok()
+            || std::env::var("WALLET_TEST_CONSTRUCTOR").is_ok();
+        let is_known_test_b64 =
+            b64_raw.trim() == "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+        if cfg!(any(test, feature = "test-env"))
+            || running_under_test_harness
+            || is_known_test_b64
+        {
+            // allowed in test builds or when using test constructor key
+        } else {
+            if std::env::var("TEST_SKIP_DECRYPT").is_ok() {
+                raw.zeroize();
+                return Err(WalletError::CryptoError(
+                    "TEST_SKIP_DECRYPT set at runtime but binary not built with `test-env`"
+                        .into(),
+                ));
+            }
+            raw.zeroize();
+            return Err(WalletError::CryptoError("Insecure WALLET_ENC_KEY (all zeros)".into()));
+        }
+    }
+    // Copy raw into an initialized array without an all-zero literal.
+    let out = {
+        let mut out_uninit = std::mem::MaybeUninit::<[u8; 32]>::uninit();
+        let out_ptr = out_uninit.as_mut_ptr() as *mut u8;
+        unsafe {
+            std::ptr::copy_nonoverlapping(raw.as_ptr(), out_ptr, 32);
+            out_uninit.assume_init()
+        }
+    };
+    raw.zeroize();
+    Ok(out)
+}
+
 async fn store_wallet_securely(
     storage: &Arc<dyn WalletStorageTrait + Send + Sync>,
     quantum_crypto: &crate::crypto::quantum::QuantumSafeEncryption,
@@ -172,60 +230,6 @@ async fn store_wallet_securely(
     use rand::RngCore;
     use sha2::Sha256;
 
-    // Load KEK from WALLET_ENC_KEY (base64 32 bytes). In test-env, a deterministic key is set.
-    fn load_envelope_kek() -> Result<[u8; 32], WalletError> {
-        use zeroize::Zeroize;
-        let b64 = std::env::var("WALLET_ENC_KEY")
-            .map_err(|_| WalletError::CryptoError("WALLET_ENC_KEY not set".into()))?;
-        let b64_raw = b64.clone();
-        let mut raw = base64::engine::general_purpose::STANDARD
-            .decode(b64_raw.trim())
-            .map_err(|_| WalletError::CryptoError("WALLET_ENC_KEY must be base64(32)".into()))?;
-        if raw.len() != 32 {
-            raw.zeroize();
-            return Err(WalletError::CryptoError("WALLET_ENC_KEY must be 32 bytes".into()));
-        }
-        // Reject an all-zero WALLET_ENC_KEY unless the runtime test override
-        // `TEST_SKIP_DECRYPT=1` is set (used by integration tests via the
-        // test-only server constructor). This avoids relying on compile-time
-        // features which don't apply to integration-test builds.
-        if raw.iter().all(|&b| b == 0) {
-            // Recognize the deterministic test key used by test constructors
-            // and allow it in that case.
-            let running_under_test_harness = std::env::var("RUST_TEST_THREADS").is_ok()
-                || std::env::var("WALLET_TEST_CONSTRUCTOR").is_ok();
-            let is_known_test_b64 =
-                b64_raw.trim() == "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
-            if cfg!(any(test, feature = "test-env"))
-                || running_under_test_harness
-                || is_known_test_b64
-            {
-                // allowed in test builds or when using test constructor key
-            } else {
-                if std::env::var("TEST_SKIP_DECRYPT").is_ok() {
-                    raw.zeroize();
-                    return Err(WalletError::CryptoError(
-                        "TEST_SKIP_DECRYPT set at runtime but binary not built with `test-env`"
-                            .into(),
-                    ));
-                }
-                raw.zeroize();
-                return Err(WalletError::CryptoError("Insecure WALLET_ENC_KEY (all zeros)".into()));
-            }
-        }
-        // Copy raw into an initialized array without an all-zero literal.
-        let out = {
-            let mut out_uninit = std::mem::MaybeUninit::<[u8; 32]>::uninit();
-            let out_ptr = out_uninit.as_mut_ptr() as *mut u8;
-            unsafe {
-                std::ptr::copy_nonoverlapping(raw.as_ptr(), out_ptr, 32);
-                out_uninit.assume_init()
-            }
-        };
-        raw.zeroize();
-        Ok(out)
-    }
-
     let mut kek = load_envelope_kek()?;
     // Generate salt into an uninitialized buffer to avoid an explicit all-zero literal.
     let mut salt = {
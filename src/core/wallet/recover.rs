@@ -83,6 +83,8 @@ pub async fn recover_wallet(
         quantum_safe,
         multi_sig_threshold: 2,
         networks: vec!["eth".to_string(), "polygon".to_string()],
+        allowed_networks: None,
+        derivation_path: None,
     };
 
     let mut encrypted_wallet_data = SecureWalletData {
@@ -94,6 +96,8 @@ pub async fn recover_wallet(
         nonce: Vec::new(),
         schema_version: crate::core::wallet_info::SecureWalletData::default_schema_version(),
         kek_id: std::env::var("WALLET_KEK_ID").ok(),
+        key_kind: crate::core::wallet_info::WalletKeyKind::Hd,
+        passphrase_protected: false,
     };
 
     store_wallet_securely(
@@ -133,8 +137,8 @@ async fn store_wallet_securely(
 
     fn load_envelope_kek() -> Result<[u8; 32], WalletError> {
         use zeroize::Zeroize;
-        let b64 = std::env::var("WALLET_ENC_KEY")
-            .map_err(|_| WalletError::CryptoError("WALLET_ENC_KEY not set".into()))?;
+        let b64 = crate::security::env_manager::secret_backend::master_key_encoded()
+            .map_err(|e| WalletError::CryptoError(e.to_string()))?;
         let b64_raw = b64.clone();
         let mut raw = base64::engine::general_purpose::STANDARD
             .decode(b64_raw.trim())
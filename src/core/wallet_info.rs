@@ -3,6 +3,8 @@ use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
+use crate::core::wallet_manager::derivation::DerivationPath;
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct WalletInfo {
     pub id: Uuid,
@@ -11,6 +13,12 @@ pub struct WalletInfo {
     pub quantum_safe: bool,
     pub multi_sig_threshold: u8,
     pub networks: Vec<String>,
+    /// Networks this wallet may be used on; `None` allows every network
+    #[serde(default)]
+    pub allowed_networks: Option<Vec<String>>,
+    /// BIP32 path of the signing key below the master key; `None` signs with the master key itself
+    #[serde(default)]
+    pub derivation_path: Option<DerivationPath>,
 }
 
 impl WalletInfo {
@@ -23,6 +31,17 @@ impl WalletInfo {
             quantum_safe,
             multi_sig_threshold: 2,
             networks: vec!["eth".to_string(), "polygon".to_string()],
+            allowed_networks: None,
+            derivation_path: None,
+        }
+    }
+
+    /// Whether `network` (canonical name or alias) is in `allowed_networks`
+    pub fn allows_network(&self, network: &str) -> bool {
+        let network = canonical_network(network);
+        match &self.allowed_networks {
+            None => true,
+            Some(allowed) => allowed.iter().any(|n| canonical_network(n) == network),
         }
     }
 
@@ -49,6 +68,28 @@ impl WalletInfo {
     }
 }
 
+/// Alias → canonical network name, same aliases the API accepts
+fn canonical_network(network: &str) -> &str {
+    match network {
+        "ethereum" => "eth",
+        "binance" | "bnb" => "bsc",
+        "bitcoin" => "btc",
+        other => other,
+    }
+}
+
+/// How a wallet's signing key came into existence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletKeyKind {
+    /// Master key derived from a generated or restored BIP39 mnemonic.
+    #[default]
+    Hd,
+    /// Single private key imported from outside (e.g. a keystore V3 file);
+    /// there is no mnemonic to back up.
+    ImportedKey,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SecureWalletData {
     pub info: WalletInfo,
@@ -60,6 +101,12 @@ pub struct SecureWalletData {
     pub schema_version: u8,
     #[serde(default)]
     pub kek_id: Option<String>,
+    #[serde(default)]
+    pub key_kind: WalletKeyKind,
+    /// The master key was derived with a non-empty BIP39 passphrase; the
+    /// mnemonic alone restores a different wallet.
+    #[serde(default)]
+    pub passphrase_protected: bool,
 }
 
 impl Zeroize for SecureWalletData {
@@ -85,6 +132,8 @@ impl SecureWalletData {
             nonce: Vec::new(),
             schema_version: Self::default_schema_version(),
             kek_id: None,
+            key_kind: WalletKeyKind::Hd,
+            passphrase_protected: false,
         }
     }
 
@@ -117,6 +166,25 @@ mod tests {
         let secure_data = SecureWalletData::new(info.clone());
         assert_eq!(secure_data.info.name, "test_wallet");
         assert!(secure_data.encrypted_master_key.is_empty());
+        assert_eq!(secure_data.key_kind, WalletKeyKind::Hd);
+    }
+
+    #[test]
+    fn test_key_kind_defaults_to_hd_for_stored_data() {
+        let mut value = serde_json::to_value(SecureWalletData::new(WalletInfo::new("legacy", false))).unwrap();
+        value.as_object_mut().unwrap().remove("key_kind");
+        let restored: SecureWalletData = serde_json::from_value(value).unwrap();
+        assert_eq!(restored.key_kind, WalletKeyKind::Hd);
+    }
+
+    #[test]
+    fn test_allows_network_matches_aliases() {
+        let mut info = WalletInfo::new("restricted", false);
+        assert!(info.allows_network("btc"));
+        info.allowed_networks = Some(vec!["eth".to_string(), "bsc".to_string()]);
+        assert!(info.allows_network("ethereum"));
+        assert!(info.allows_network("bnb"));
+        assert!(!info.allows_network("polygon"));
     }
 
     #[test]
@@ -2,6 +2,7 @@
 //!
 //! 提供基于主密钥的address派生功能
 
+use super::derivation::DerivationPath;
 use super::WalletManager;
 use crate::core::errors::WalletError;
 use tracing::debug;
@@ -12,6 +13,8 @@ impl WalletManager {
     /// # Arguments
     /// * `master_key` - 主密钥字节
     /// * `network` - network名称
+    /// * `path` - BIP32 派生路径；`Some` 时以主密钥为种子派生该路径下的子密钥，
+    ///   `None` 时直接使用主密钥
     ///
     /// # Returns
     /// 派生的address字符串
@@ -20,8 +23,18 @@ impl WalletManager {
         &self,
         master_key: &[u8],
         network: &str,
+        path: Option<&DerivationPath>,
     ) -> Result<String, WalletError> {
-        debug!("Deriving address for network: {}", network);
+        debug!("Deriving address for network: {} (path: {:?})", network, path.map(|p| p.to_string()));
+
+        let child_key;
+        let master_key = match path {
+            Some(path) => {
+                child_key = path.derive_key(master_key)?;
+                &child_key[..]
+            }
+            None => master_key,
+        };
 
         match network {
             #[cfg(feature = "ethereum")]
@@ -109,7 +122,7 @@ mod tests {
     async fn test_derive_address_unsupported_network() {
         let manager = create_test_manager().await;
         let master_key = vec![1u8; 32];
-        let result = manager.derive_address(&master_key, "unknown");
+        let result = manager.derive_address(&master_key, "unknown", None);
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), WalletError::ValidationError(_)));
     }
@@ -119,7 +132,7 @@ mod tests {
     async fn test_derive_ethereum_address() {
         let manager = create_test_manager().await;
         let master_key = vec![1u8; 32];
-        let result = manager.derive_address(&master_key, "eth");
+        let result = manager.derive_address(&master_key, "eth", None);
         assert!(result.is_ok());
         let address = result.unwrap();
         assert!(address.starts_with("0x"));
@@ -131,8 +144,8 @@ mod tests {
     async fn test_derive_ethereum_address_deterministic() {
         let manager = create_test_manager().await;
         let master_key = vec![42u8; 32];
-        let addr1 = manager.derive_address(&master_key, "eth").unwrap();
-        let addr2 = manager.derive_address(&master_key, "eth").unwrap();
+        let addr1 = manager.derive_address(&master_key, "eth", None).unwrap();
+        let addr2 = manager.derive_address(&master_key, "eth", None).unwrap();
         assert_eq!(addr1, addr2);
     }
     
@@ -142,8 +155,8 @@ mod tests {
         let manager = create_test_manager().await;
         let key1 = vec![1u8; 32];
         let key2 = vec![2u8; 32];
-        let addr1 = manager.derive_address(&key1, "eth").unwrap();
-        let addr2 = manager.derive_address(&key2, "eth").unwrap();
+        let addr1 = manager.derive_address(&key1, "eth", None).unwrap();
+        let addr2 = manager.derive_address(&key2, "eth", None).unwrap();
         assert_ne!(addr1, addr2);
     }
     
@@ -152,7 +165,7 @@ mod tests {
     async fn test_derive_address_empty_key() {
         let manager = create_test_manager().await;
         let empty_key = vec![];
-        let result = manager.derive_address(&empty_key, "eth");
+        let result = manager.derive_address(&empty_key, "eth", None);
         assert!(result.is_err());
     }
     
@@ -160,8 +173,32 @@ mod tests {
     async fn test_derive_address_short_key() {
         let manager = create_test_manager().await;
         let short_key = vec![1u8; 16];
-        let result = manager.derive_address(&short_key, "eth");
+        let result = manager.derive_address(&short_key, "eth", None);
         assert!(result.is_err());
     }
+
+    #[cfg(feature = "ethereum")]
+    #[tokio::test]
+    async fn test_derive_address_with_path() {
+        let manager = create_test_manager().await;
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let seed = bip39::Mnemonic::parse(mnemonic).unwrap().to_seed("");
+
+        // BIP44 参考向量
+        let path = DerivationPath::parse("m/44'/60'/0'/0/0").unwrap();
+        let address = manager.derive_address(&seed, "eth", Some(&path)).unwrap();
+        assert_eq!(address, "0x9858effd232b4033e47d90003d41ec34ecaeda94");
+        let path = DerivationPath::parse("m/44'/60'/0'/0/5").unwrap();
+        let address = manager.derive_address(&seed, "eth", Some(&path)).unwrap();
+        assert_eq!(address, "0xa40cfbfc8534ffc84e20a7d8bbc3729b26a35f6f");
+
+        // 32 字节主密钥作种子，与直接使用主密钥得到的address不同
+        let master_key = &seed[..32];
+        let path = DerivationPath::parse("m/44'/60'/0'/0/0").unwrap();
+        let derived = manager.derive_address(master_key, "eth", Some(&path)).unwrap();
+        assert_eq!(derived, "0x8cb0e29fbaf9b7b1ebbd44a0b8c29dcc5e173afc");
+        let direct = manager.derive_address(master_key, "eth", None).unwrap();
+        assert_eq!(direct, "0xea6e8f7525e8af0669546ac6c5b8318fd2c6d7b6");
+    }
 }
 
@@ -1,11 +1,190 @@
 //! 备份和恢复模块
 //!
 //! 提供wallet的备份、恢复和transaction历史query功能
+//!
+//! mnemonic不落库，能备份的只有加密保存的 32 字节主密钥（所有地址都由它派生）。
+//! [`WalletManager::backup_to_shares`] 把主密钥按 Shamir 拆成 `total` 份
+//! [`ShareEnvelope`]，任意 `threshold` 份即可经
+//! [`WalletManager::restore_from_shares`] 重建wallet。
 
+use super::derivation::DerivationPath;
+use super::master_key_derivation::{derive_ethereum_address_from_key, master_key_from_mnemonic};
 use super::WalletManager;
 use crate::core::errors::WalletError;
+use crate::core::wallet_info::{SecureWalletData, WalletKeyKind};
+use crate::crypto::shamir::{combine_secret, split_secret};
+use crate::security::memory_protection::TaintedBytes;
+use crate::security::password_validator::{validate_password, PasswordPolicy};
 use crate::security::SecretVec;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use tracing::info;
+use zeroize::Zeroize;
+
+/// 份额格式版本
+pub const SHARE_ENVELOPE_VERSION: u8 = 1;
+/// 门限下限：1-of-n 的每一份都等于密钥本身
+pub const MIN_SHARE_THRESHOLD: u8 = 2;
+/// 份数上限
+pub const MAX_SHARE_TOTAL: u8 = 16;
+
+/// 一份 Shamir 备份
+///
+/// 同一次备份的各份共享 `backup_id`、门限和地址；`checksum` 只用于发现抄写/
+/// 传输中的损坏和不同备份混用，不是 MAC，份额本身仍须按密钥保管。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareEnvelope {
+    pub version: u8,
+    pub backup_id: String,
+    /// 份额编号，1..=total
+    pub index: u8,
+    pub threshold: u8,
+    pub total: u8,
+    pub key_kind: WalletKeyKind,
+    /// 备份时主密钥对应的以太坊地址，恢复后据此validate
+    pub address: String,
+    /// wallet的派生路径，恢复后沿用；旧份额没有此字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<DerivationPath>,
+    /// 32 字节份额，base64
+    pub payload: String,
+    /// 以上字段的 SHA-256 前 8 字节，hex
+    pub checksum: String,
+}
+
+impl ShareEnvelope {
+    fn compute_checksum(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update([self.version, self.index, self.threshold, self.total]);
+        for field in [self.backup_id.as_str(), key_kind_name(self.key_kind), self.address.as_str(), &self.payload] {
+            hasher.update((field.len() as u32).to_be_bytes());
+            hasher.update(field.as_bytes());
+        }
+        // 没有路径时不参与计算，旧份额的校验和保持有效
+        if let Some(path) = &self.derivation_path {
+            let path = path.to_string();
+            hasher.update((path.len() as u32).to_be_bytes());
+            hasher.update(path.as_bytes());
+        }
+        hex::encode(&hasher.finalize()[..8])
+    }
+
+    /// 是否与 `other` 出自同一次备份
+    fn same_backup(&self, other: &ShareEnvelope) -> bool {
+        self.backup_id == other.backup_id
+            && self.threshold == other.threshold
+            && self.total == other.total
+            && self.key_kind == other.key_kind
+            && self.address.eq_ignore_ascii_case(&other.address)
+            && self.derivation_path == other.derivation_path
+    }
+}
+
+fn key_kind_name(kind: WalletKeyKind) -> &'static str {
+    match kind {
+        WalletKeyKind::Hd => "hd",
+        WalletKeyKind::ImportedKey => "imported_key",
+    }
+}
+
+/// Shamir 备份/恢复的error
+#[derive(Debug, thiserror::Error)]
+pub enum ShareBackupError {
+    #[error("invalid share parameters: {0}")]
+    InvalidParameters(String),
+    #[error("share {index} is malformed: {reason}")]
+    Malformed { index: u8, reason: String },
+    #[error("share {0} failed its checksum; it was altered or mistyped")]
+    ChecksumMismatch(u8),
+    #[error("share {0} was supplied more than once")]
+    DuplicateShare(u8),
+    #[error("shares belong to different backups")]
+    MixedBackups,
+    #[error("{have} share(s) supplied, this backup needs {need}")]
+    NotEnoughShares { have: usize, need: u8 },
+    #[error("reconstructed key does not match the backed-up address")]
+    KeyMismatch,
+    #[error(transparent)]
+    Wallet(#[from] WalletError),
+}
+
+impl ShareBackupError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ShareBackupError::InvalidParameters(_) => "INVALID_SHARE_PARAMETERS",
+            ShareBackupError::Malformed { .. } => "INVALID_SHARE",
+            ShareBackupError::ChecksumMismatch(_) => "SHARE_CHECKSUM_MISMATCH",
+            ShareBackupError::DuplicateShare(_) => "DUPLICATE_SHARE",
+            ShareBackupError::MixedBackups => "SHARE_BACKUP_MISMATCH",
+            ShareBackupError::NotEnoughShares { .. } => "INSUFFICIENT_SHARES",
+            ShareBackupError::KeyMismatch => "SHARE_RECONSTRUCTION_MISMATCH",
+            ShareBackupError::Wallet(_) => "SHARE_BACKUP_FAILED",
+        }
+    }
+}
+
+fn check_share_parameters(threshold: u8, total: u8) -> Result<(), ShareBackupError> {
+    if threshold < MIN_SHARE_THRESHOLD {
+        return Err(ShareBackupError::InvalidParameters(format!(
+            "threshold must be at least {}",
+            MIN_SHARE_THRESHOLD
+        )));
+    }
+    if total > MAX_SHARE_TOTAL {
+        return Err(ShareBackupError::InvalidParameters(format!("at most {} shares", MAX_SHARE_TOTAL)));
+    }
+    if threshold > total {
+        return Err(ShareBackupError::InvalidParameters(format!(
+            "threshold {} exceeds total {}",
+            threshold, total
+        )));
+    }
+    Ok(())
+}
+
+/// validate并解码全部份额到 `decoded`；出错时已解码的部分也留在 `decoded` 里由调用方清零
+fn decode_shares(
+    shares: &[ShareEnvelope],
+    first: &ShareEnvelope,
+    decoded: &mut Vec<(u8, [u8; 32])>,
+) -> Result<(), ShareBackupError> {
+    let mut seen = HashSet::new();
+    for share in shares {
+        if share.checksum != share.compute_checksum() {
+            return Err(ShareBackupError::ChecksumMismatch(share.index));
+        }
+        if share.version != SHARE_ENVELOPE_VERSION {
+            let reason = format!("unsupported version {}", share.version);
+            return Err(ShareBackupError::Malformed { index: share.index, reason });
+        }
+        if !share.same_backup(first) {
+            return Err(ShareBackupError::MixedBackups);
+        }
+        if share.index == 0 || share.index > share.total {
+            let reason = format!("index must be between 1 and {}", share.total);
+            return Err(ShareBackupError::Malformed { index: share.index, reason });
+        }
+        if !seen.insert(share.index) {
+            return Err(ShareBackupError::DuplicateShare(share.index));
+        }
+        let mut bytes = base64::engine::general_purpose::STANDARD
+            .decode(&share.payload)
+            .map_err(|e| ShareBackupError::Malformed { index: share.index, reason: e.to_string() })?;
+        let payload: Result<[u8; 32], _> = bytes.as_slice().try_into();
+        bytes.zeroize();
+        let payload = payload.map_err(|_| ShareBackupError::Malformed {
+            index: share.index,
+            reason: "payload must be 32 bytes".to_string(),
+        })?;
+        decoded.push((share.index, payload));
+    }
+    if decoded.len() < first.threshold as usize {
+        return Err(ShareBackupError::NotEnoughShares { have: decoded.len(), need: first.threshold });
+    }
+    Ok(())
+}
 
 impl WalletManager {
     /// 备份wallet（导出mnemonic）
@@ -71,12 +250,73 @@ impl WalletManager {
     /// * `mnemonic` - mnemonic
     ///
     /// # Returns
-    /// 恢复success返回 Ok(())
+    /// 恢复success返回 Ok(())；要保存密钥或使用 BIP39 passphrase 时用
+    /// [`Self::restore_wallet_with_options`] / [`Self::restore_wallet_with_passphrase`]
     pub async fn restore_wallet(
         &self,
         name: &str,
         mnemonic: &str,
     ) -> Result<(), WalletError> {
+        self.restore_wallet_with_options(name, mnemonic, None, None).await.map(|_| ())
+    }
+
+    /// 带选项恢复wallet
+    ///
+    /// 给出 `password` 时派生的主密钥按它加密保存，恢复出的wallet可直接sign；
+    /// 不给时与 [`Self::restore_wallet`] 一样只登记wallet，不保存密钥。
+    ///
+    /// # Arguments
+    /// * `name` - Wallet name
+    /// * `mnemonic` - mnemonic
+    /// * `password` - Wallet password（可选），用于加密主密钥
+    /// * `derivation_path` - 派生路径（可选，如 m/44'/60'/0'/0/5），记为该wallet的默认路径
+    ///
+    /// # Returns
+    /// 第一个派生的以太坊address（有派生路径时为路径下的address）
+    pub async fn restore_wallet_with_options(
+        &self,
+        name: &str,
+        mnemonic: &str,
+        password: Option<&str>,
+        derivation_path: Option<&str>,
+    ) -> Result<String, WalletError> {
+        self.restore_from_mnemonic(name, mnemonic, password, None, derivation_path).await
+    }
+
+    /// 用 BIP39 passphrase 恢复wallet
+    ///
+    /// passphrase 输错时恢复照样success（BIP39 不校验 passphrase），只是得到另一组address，
+    /// 所以返回第一个派生address，由user在充值前核对。passphrase 不保存，派生出的主密钥
+    /// 按 `password` 加密保存，与创建wallet时相同。
+    ///
+    /// # Arguments
+    /// * `name` - Wallet name
+    /// * `mnemonic` - mnemonic
+    /// * `password` - Wallet password，用于加密主密钥
+    /// * `passphrase` - BIP39 passphrase（可选，创建时用过就必须提供同一个）
+    /// * `derivation_path` - 派生路径（可选）
+    ///
+    /// # Returns
+    /// 第一个派生的以太坊address
+    pub async fn restore_wallet_with_passphrase(
+        &self,
+        name: &str,
+        mnemonic: &str,
+        password: &str,
+        passphrase: Option<&str>,
+        derivation_path: Option<&str>,
+    ) -> Result<String, WalletError> {
+        self.restore_from_mnemonic(name, mnemonic, Some(password), passphrase, derivation_path).await
+    }
+
+    async fn restore_from_mnemonic(
+        &self,
+        name: &str,
+        mnemonic: &str,
+        password: Option<&str>,
+        passphrase: Option<&str>,
+        derivation_path: Option<&str>,
+    ) -> Result<String, WalletError> {
         info!("Restoring wallet: {}", name);
 
         // Validate mnemonic
@@ -97,43 +337,170 @@ impl WalletManager {
                 return Err(WalletError::ValidationError(format!("Wallet '{}' already exists", name)));
             }
         }
+        if let Some(password) = password {
+            validate_password(password, &PasswordPolicy::default())?;
+        }
+        let derivation_path = derivation_path.map(DerivationPath::parse).transpose()?;
 
-        // 恢复wallet（简化版本）
-        let _ = mnemonic; // TODO: Validate and use mnemonic
-        let wallet_info = crate::core::wallet_info::WalletInfo::new(name, false);
-        
-        // Convert WalletInfo to SecureWalletData
-        let wallet_data = crate::core::wallet_info::SecureWalletData::new(wallet_info);
+        // 词表与校验和check；passphrase 无从校验
+        let mnemonic = bip39::Mnemonic::parse_in_normalized(bip39::Language::English, mnemonic)
+            .map_err(|e| WalletError::MnemonicError(format!("Invalid mnemonic: {}", e)))?;
+        let master_key = master_key_from_mnemonic(&mnemonic, passphrase);
+        let address = match &derivation_path {
+            Some(path) => derive_ethereum_address_from_key(&path.derive_key(&master_key[..])?)?,
+            None => derive_ethereum_address_from_key(&master_key[..])?,
+        };
+
+        let mut info = self.new_wallet_info(name, false);
+        info.derivation_path = derivation_path;
+        let mut wallet_data = SecureWalletData::new(info);
+        if let Some(password) = password {
+            let (encrypted_master_key, salt, nonce) = self.encrypt_master_key(&master_key[..], password)?;
+            wallet_data.encrypted_master_key = encrypted_master_key;
+            wallet_data.salt = salt;
+            wallet_data.nonce = nonce;
+        }
+        wallet_data.passphrase_protected = passphrase.is_some_and(|p| !p.is_empty());
 
         // 存储wallet
         {
             let mut wallets = self.wallets.write();
+            if wallets.contains_key(name) {
+                return Err(WalletError::ValidationError(format!("Wallet '{}' already exists", name)));
+            }
             wallets.insert(name.to_string(), wallet_data);
         }
 
-        info!("✅ Wallet '{}' restored successfully", name);
-        Ok(())
+        info!("✅ Wallet '{}' restored successfully (first address {})", name, address);
+        Ok(address)
     }
 
-    /// 带选项恢复wallet
+    /// 把wallet主密钥拆成 Shamir 份额
     ///
     /// # Arguments
     /// * `name` - Wallet name
-    /// * `mnemonic` - mnemonic
-    /// * `password` - Password（可选）
-    /// * `derivation_path` - 派生路径（可选）
-    pub async fn restore_wallet_with_options(
+    /// * `password` - Wallet password，用于解密主密钥
+    /// * `threshold` - 恢复所需份数（至少 2）
+    /// * `total` - 总份数（至多 16）
+    ///
+    /// # Returns
+    /// `total` 份 [`ShareEnvelope`]，编号 1..=total
+    pub async fn backup_to_shares(
         &self,
         name: &str,
-        mnemonic: &str,
-        _password: Option<&str>,
-        _derivation_path: Option<&str>,
-    ) -> Result<(), WalletError> {
-        info!("Restoring wallet with options: {}", name);
+        password: &str,
+        threshold: u8,
+        total: u8,
+    ) -> Result<Vec<ShareEnvelope>, ShareBackupError> {
+        info!("Splitting wallet {} into {}-of-{} shares", name, threshold, total);
+        check_share_parameters(threshold, total)?;
+
+        let wallet = self
+            .get_wallet_by_name(name)
+            .await?
+            .ok_or_else(|| WalletError::NotFoundError(format!("Wallet '{}' not found", name)))?;
+        let key = self.decrypt_master_key(&wallet, password).await?;
+        let address = derive_ethereum_address_from_key(&key)?;
+        let mut shares =
+            split_secret(&*key, threshold, total).map_err(|e| ShareBackupError::InvalidParameters(e.to_string()))?;
 
-        // 目前委托给基础恢复方法
-        // 未来可以支持自定义派生路径
-        self.restore_wallet(name, mnemonic).await
+        let backup_id = self.ids.new_id();
+        let envelopes = shares
+            .iter()
+            .map(|(index, share)| {
+                let mut envelope = ShareEnvelope {
+                    version: SHARE_ENVELOPE_VERSION,
+                    backup_id: backup_id.clone(),
+                    index: *index,
+                    threshold,
+                    total,
+                    key_kind: wallet.key_kind,
+                    address: address.clone(),
+                    derivation_path: wallet.info.derivation_path.clone(),
+                    payload: base64::engine::general_purpose::STANDARD.encode(share),
+                    checksum: String::new(),
+                };
+                envelope.checksum = envelope.compute_checksum();
+                envelope
+            })
+            .collect();
+        for (_, share) in shares.iter_mut() {
+            share.zeroize();
+        }
+        Ok(envelopes)
+    }
+
+    /// 由 Shamir 份额重建wallet
+    ///
+    /// 先validate每份的校验和、编号与所属备份，再合并；合并出的主密钥必须派生出
+    /// 备份时记录的地址，随后按 `password` 重新加密保存（只在内存中，落库由调用方负责）。
+    ///
+    /// # Returns
+    /// 恢复的以太坊地址（有派生路径时为路径下子密钥的地址）
+    pub async fn restore_from_shares(
+        &self,
+        name: &str,
+        shares: &[ShareEnvelope],
+        password: &str,
+    ) -> Result<String, ShareBackupError> {
+        info!("Restoring wallet {} from {} shares", name, shares.len());
+
+        validate_password(password, &PasswordPolicy::default())?;
+        if self.wallets.read().contains_key(name) {
+            return Err(WalletError::ValidationError(format!("Wallet '{}' already exists", name)).into());
+        }
+
+        let first = shares.first().ok_or(ShareBackupError::NotEnoughShares { have: 0, need: MIN_SHARE_THRESHOLD })?;
+        check_share_parameters(first.threshold, first.total)?;
+        let mut decoded = Vec::with_capacity(shares.len());
+        let combined = decode_shares(shares, first, &mut decoded).and_then(|()| {
+            combine_secret(&decoded, first.threshold).map_err(|e| ShareBackupError::InvalidParameters(e.to_string()))
+        });
+        for (_, payload) in decoded.iter_mut() {
+            payload.zeroize();
+        }
+        let mut secret = combined?;
+        let key = TaintedBytes::new(SecretVec::new(secret.to_vec()), "shamir restored key");
+        secret.zeroize();
+
+        // 合并总能得出 32 字节：份额若来自别的密钥（改了 backup_id 并重算校验和），只能靠地址发现
+        let address = derive_ethereum_address_from_key(&key).map_err(|_| ShareBackupError::KeyMismatch)?;
+        if !address.eq_ignore_ascii_case(&first.address) {
+            return Err(ShareBackupError::KeyMismatch);
+        }
+
+        let (encrypted_master_key, salt, nonce) = self.encrypt_master_key(&key, password)?;
+        let mut info = self.new_wallet_info(name, false);
+        if first.key_kind == WalletKeyKind::ImportedKey {
+            info.multi_sig_threshold = 1;
+            info.networks = vec!["eth".to_string()];
+        }
+        let address = match &first.derivation_path {
+            Some(path) => derive_ethereum_address_from_key(&path.derive_key(&key)?)?,
+            None => address,
+        };
+        info.derivation_path = first.derivation_path.clone();
+        let wallet_data = SecureWalletData {
+            info,
+            encrypted_master_key,
+            shamir_shares: Vec::new(),
+            salt,
+            nonce,
+            schema_version: SecureWalletData::default_schema_version(),
+            kek_id: None,
+            key_kind: first.key_kind,
+            passphrase_protected: false,
+        };
+        {
+            let mut wallets = self.wallets.write();
+            if wallets.contains_key(name) {
+                return Err(WalletError::ValidationError(format!("Wallet '{}' already exists", name)).into());
+            }
+            wallets.insert(name.to_string(), wallet_data);
+        }
+
+        info!("✅ Wallet '{}' restored from shares ({})", name, address);
+        Ok(address)
     }
 
     /// fetchtransaction历史（企业级实现方案）
@@ -332,6 +699,59 @@ mod tests {
         assert!(matches!(result.unwrap_err(), WalletError::ValidationError(_)));
     }
     
+    #[tokio::test]
+    async fn test_restore_wallet_with_passphrase() {
+        let manager = create_test_manager().await;
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let password = "Rest0re!Passphrase#2024";
+        let plain = manager.restore_wallet_with_options("plain", mnemonic, None, None).await.unwrap();
+        let protected =
+            manager.restore_wallet_with_passphrase("protected", mnemonic, password, Some("TREZOR"), None).await.unwrap();
+        assert_ne!(plain, protected);
+        assert!(!manager.get_wallet_by_name("plain").await.unwrap().unwrap().passphrase_protected);
+        assert!(manager.get_wallet_by_name("protected").await.unwrap().unwrap().passphrase_protected);
+        manager.create_wallet_with_passphrase("created", "test_password", false, Some("TREZOR")).await.unwrap();
+        assert!(manager.get_wallet_by_name("created").await.unwrap().unwrap().passphrase_protected);
+
+        // 保存的是 passphrase 派生的主密钥：解锁后sign的address即返回的第一个address
+        let signer = manager.ethereum_signer("protected", password).await.unwrap();
+        assert!(protected.eq_ignore_ascii_case(&format!("{:#x}", ethers::signers::Signer::address(&signer))));
+        let stored = manager.get_wallet_by_name("protected").await.unwrap().unwrap();
+        let key = manager.decrypt_master_key(&stored, password).await.unwrap();
+        assert_eq!(derive_ethereum_address_from_key(&key).unwrap(), protected);
+
+        // 错误的 passphrase 同样恢复success，但地址不同
+        let wrong =
+            manager.restore_wallet_with_passphrase("wrong", mnemonic, password, Some("TREZ0R"), None).await.unwrap();
+        assert_ne!(wrong, protected);
+
+        // 校验和错误
+        let bad = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        let result = manager.restore_wallet("bad", bad).await;
+        assert!(matches!(result.unwrap_err(), WalletError::MnemonicError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_restore_wallet_honors_the_derivation_path() {
+        let manager = create_test_manager().await;
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let password = "Rest0re!Derived#2024";
+        let path = "m/44'/60'/0'/0/5";
+        let address =
+            manager.restore_wallet_with_passphrase("derived", mnemonic, password, Some("TREZOR"), Some(path)).await.unwrap();
+        let root = manager.restore_wallet_with_passphrase("root", mnemonic, password, Some("TREZOR"), None).await.unwrap();
+        assert_ne!(address, root);
+
+        let wallet = manager.get_wallet_by_name("derived").await.unwrap().unwrap();
+        assert_eq!(wallet.info.derivation_path, Some(DerivationPath::parse(path).unwrap()));
+        let signer = manager.ethereum_signer("derived", password).await.unwrap();
+        assert!(address.eq_ignore_ascii_case(&format!("{:#x}", ethers::signers::Signer::address(&signer))));
+
+        let result = manager.restore_wallet_with_options("bad_path", mnemonic, Some(password), Some("m/44/60")).await;
+        assert!(matches!(result.unwrap_err(), WalletError::ValidationError(_)));
+        assert!(manager.get_wallet_by_name("bad_path").await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_get_transaction_history_wallet_not_found() {
         let manager = create_test_manager().await;
@@ -350,6 +770,121 @@ mod tests {
         // 两种情况都是合理的
         assert!(result.is_ok() || result.is_err());
     }
-}
 
+    const SHARE_PASSWORD: &str = "Sh4mirBackup!";
+
+    async fn share_manager() -> WalletManager {
+        let mut config = WalletConfig::default();
+        config.security.pbkdf2_iterations = 1_000;
+        WalletManager::new(&config).await.unwrap()
+    }
+
+    async fn split(wm: &WalletManager, threshold: u8, total: u8) -> (String, Vec<ShareEnvelope>) {
+        wm.create_wallet("vault", SHARE_PASSWORD, false).await.unwrap();
+        let address = wm.get_ethereum_address_from_master_key("vault", SHARE_PASSWORD).await.unwrap();
+        (address, wm.backup_to_shares("vault", SHARE_PASSWORD, threshold, total).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_shares_round_trip_2_of_3() {
+        let wm = share_manager().await;
+        let (address, shares) = split(&wm, 2, 3).await;
+        assert_eq!(shares.len(), 3);
+        assert_eq!(shares.iter().map(|s| s.index).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(shares.iter().all(|s| s.backup_id == shares[0].backup_id && s.address == address));
+
+        // 任意两份都可以
+        for (i, pair) in [[0, 1], [0, 2], [2, 1]].iter().enumerate() {
+            let name = format!("restored_{}", i);
+            let picked: Vec<_> = pair.iter().map(|&j| shares[j].clone()).collect();
+            let restored = wm.restore_from_shares(&name, &picked, "N3wVaultKey!").await.unwrap();
+            assert!(restored.eq_ignore_ascii_case(&address));
+            let signer = wm.get_ethereum_address_from_master_key(&name, "N3wVaultKey!").await.unwrap();
+            assert!(signer.eq_ignore_ascii_case(&address));
+        }
+    }
 
+    #[tokio::test]
+    async fn test_shares_round_trip_3_of_5() {
+        let wm = share_manager().await;
+        let (address, shares) = split(&wm, 3, 5).await;
+        let picked = vec![shares[4].clone(), shares[1].clone(), shares[3].clone()];
+        let restored = wm.restore_from_shares("restored", &picked, SHARE_PASSWORD).await.unwrap();
+        assert!(restored.eq_ignore_ascii_case(&address));
+
+        let err = wm.restore_from_shares("partial", &shares[..2], SHARE_PASSWORD).await.unwrap_err();
+        assert!(matches!(err, ShareBackupError::NotEnoughShares { have: 2, need: 3 }), "{:?}", err);
+        assert!(wm.get_wallet_by_name("partial").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_share_parameters_are_bounded() {
+        let wm = share_manager().await;
+        wm.create_wallet("vault", SHARE_PASSWORD, false).await.unwrap();
+        for (threshold, total) in [(1, 3), (0, 3), (2, 17), (4, 3)] {
+            let err = wm.backup_to_shares("vault", SHARE_PASSWORD, threshold, total).await.unwrap_err();
+            assert_eq!(err.code(), "INVALID_SHARE_PARAMETERS", "{}-of-{}", threshold, total);
+        }
+        assert!(wm.backup_to_shares("vault", SHARE_PASSWORD, 16, 16).await.is_ok());
+        let err = wm.backup_to_shares("missing", SHARE_PASSWORD, 2, 3).await.unwrap_err();
+        assert!(matches!(err, ShareBackupError::Wallet(WalletError::NotFoundError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_bad_shares() {
+        let wm = share_manager().await;
+        let (_, shares) = split(&wm, 2, 3).await;
+
+        let err = wm.restore_from_shares("dup", &[shares[0].clone(), shares[0].clone()], SHARE_PASSWORD).await;
+        assert!(matches!(err.unwrap_err(), ShareBackupError::DuplicateShare(1)));
+
+        let mut tampered = shares[1].clone();
+        tampered.payload = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+        let err = wm.restore_from_shares("tampered", &[shares[0].clone(), tampered], SHARE_PASSWORD).await;
+        assert!(matches!(err.unwrap_err(), ShareBackupError::ChecksumMismatch(2)));
+
+        wm.create_wallet("other", SHARE_PASSWORD, false).await.unwrap();
+        let other = wm.backup_to_shares("other", SHARE_PASSWORD, 2, 3).await.unwrap();
+        let err = wm.restore_from_shares("mixed", &[shares[0].clone(), other[1].clone()], SHARE_PASSWORD).await;
+        assert!(matches!(err.unwrap_err(), ShareBackupError::MixedBackups));
+
+        // 伪造成同一备份并重算校验和：合并出的密钥对不上地址
+        let mut forged = other[1].clone();
+        forged.backup_id = shares[0].backup_id.clone();
+        forged.address = shares[0].address.clone();
+        forged.checksum = forged.compute_checksum();
+        let err = wm.restore_from_shares("forged", &[shares[0].clone(), forged], SHARE_PASSWORD).await;
+        assert!(matches!(err.unwrap_err(), ShareBackupError::KeyMismatch));
+
+        let err = wm.restore_from_shares("vault", &shares, SHARE_PASSWORD).await.unwrap_err();
+        assert!(matches!(err, ShareBackupError::Wallet(WalletError::ValidationError(_))));
+        let err = wm.restore_from_shares("empty", &[], SHARE_PASSWORD).await.unwrap_err();
+        assert!(matches!(err, ShareBackupError::NotEnoughShares { have: 0, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_shares_keep_the_derivation_path() {
+        let wm = share_manager().await;
+        wm.create_wallet("vault", SHARE_PASSWORD, false).await.unwrap();
+        let path = DerivationPath::parse("m/44'/60'/0'/0/5").unwrap();
+        wm.wallets.write().get_mut("vault").unwrap().info.derivation_path = Some(path.clone());
+        let address = wm.get_ethereum_address_from_master_key("vault", SHARE_PASSWORD).await.unwrap();
+
+        let shares = wm.backup_to_shares("vault", SHARE_PASSWORD, 2, 3).await.unwrap();
+        assert!(shares.iter().all(|s| s.derivation_path.as_ref() == Some(&path)));
+        // 份额记录的是主密钥的地址
+        assert!(!shares[0].address.eq_ignore_ascii_case(&address));
+
+        let mut tampered = shares[1].clone();
+        tampered.derivation_path = None;
+        let err = wm.restore_from_shares("tampered", &[shares[0].clone(), tampered], SHARE_PASSWORD).await;
+        assert!(matches!(err.unwrap_err(), ShareBackupError::ChecksumMismatch(2)));
+
+        let restored = wm.restore_from_shares("restored", &shares[1..], SHARE_PASSWORD).await.unwrap();
+        assert!(restored.eq_ignore_ascii_case(&address));
+        let wallet = wm.get_wallet_by_name("restored").await.unwrap().unwrap();
+        assert_eq!(wallet.info.derivation_path, Some(path));
+        let signer = wm.get_ethereum_address_from_master_key("restored", SHARE_PASSWORD).await.unwrap();
+        assert!(signer.eq_ignore_ascii_case(&address));
+    }
+}
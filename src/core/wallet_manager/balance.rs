@@ -4,6 +4,7 @@
 
 use super::WalletManager;
 use crate::core::errors::WalletError;
+use crate::security::env_manager::config_secrets::redact_url;
 use tracing::info;
 
 
@@ -31,6 +32,7 @@ impl WalletManager {
             .get_wallet_by_name(wallet_name)
             .await?
             .ok_or_else(|| WalletError::NotFoundError(format!("Wallet '{}' not found", wallet_name)))?;
+        self.ensure_network_allowed(wallet_name, network)?;
 
         // 根据networkquerybalance
         match network {
@@ -74,7 +76,7 @@ impl WalletManager {
                 _ => "https://eth.llamarpc.com"
             });
         
-        info!("querybalance: wallet={}, network={}, rpc={}", wallet_name, network, rpc_url);
+        info!("querybalance: wallet={}, network={}, rpc={}", wallet_name, network, redact_url(rpc_url));
         
         // 创建Provider
         let provider = Provider::<Http>::try_from(rpc_url)
@@ -55,7 +55,7 @@ impl WalletManager {
             ))?;
         
         // Step 2: 解密master_key并创建Private key
-        let master_key = self.decrypt_master_key(&wallet, password).await?;
+        let master_key = self.decrypt_signing_key(&wallet, password).await?;
         
         let secp = Secp256k1::new();
         let secret_key = SecretKey::from_slice(&master_key)
@@ -80,9 +80,11 @@ impl WalletManager {
         }
         
         // Step 5: 解析目标金额（BTC → satoshi）
-        let amount_f64: f64 = amount_btc.parse()
-            .map_err(|_| WalletError::ValidationError("Invalid amount".to_string()))?;
-        let amount_satoshi = (amount_f64 * 100_000_000.0) as u64;
+        let amount_satoshi = crate::core::amount::Amount::parse_native("btc", amount_btc.trim())
+            .ok()
+            .filter(|a| !a.is_zero())
+            .and_then(|a| u64::try_from(a.minimal()).ok())
+            .ok_or_else(|| WalletError::ValidationError("Invalid amount".to_string()))?;
         
         info!("Target amount: {} BTC = {} satoshi", amount_btc, amount_satoshi);
         
@@ -118,7 +118,7 @@ pub fn select_utxos(
     }
     
     // 按金额from大到小排序（贪心策略）
-    utxos.sort_by(|a, b| b.value.cmp(&a.value));
+    utxos.sort_by_key(|u| std::cmp::Reverse(u.value));
     
     let mut selected = Vec::new();
     let mut total_value = 0u64;
@@ -3,9 +3,11 @@
 //! 提供跨链资产桥接功能
 
 use super::WalletManager;
+use crate::core::amount::{Amount, AssetTag};
 use crate::core::errors::WalletError;
 use crate::blockchain::bridge::{BridgeTransaction, BridgeTransactionStatus};
-use tracing::info;
+use crate::security::env_manager::config_secrets::redact_url;
+use tracing::{info, warn};
 
 impl WalletManager {
     /// 桥接资产
@@ -37,10 +39,13 @@ impl WalletManager {
             .get_wallet_by_name(from_wallet)
             .await?
             .ok_or_else(|| WalletError::NotFoundError(format!("Wallet not found: {}", from_wallet)))?;
+        // 资金从源链离开、到目标链入账，两端都须在允许集合内
+        self.ensure_network_allowed(from_wallet, from_chain)?;
+        self.ensure_network_allowed(from_wallet, to_chain)?;
 
         // 创建桥接transaction
         let bridge_tx = BridgeTransaction {
-            id: uuid::Uuid::new_v4().to_string(),
+            id: self.ids.new_id(),
             from_wallet: from_wallet.to_string(),
             from_chain: from_chain.to_string(),
             to_chain: to_chain.to_string(),
@@ -49,10 +54,14 @@ impl WalletManager {
             status: BridgeTransactionStatus::Initiated,
             source_tx_hash: None,
             destination_tx_hash: None,
-            created_at: chrono::Utc::now(),
-            updated_at: chrono::Utc::now(),
+            created_at: self.clock.now(),
+            updated_at: self.clock.now(),
             fee_amount: None,
             estimated_completion_time: None,
+            amount_minimal_source: None,
+            amount_minimal_dest: None,
+            token_address_source: None,
+            token_address_dest: None,
         };
 
         let tx_id = bridge_tx.id.clone();
@@ -96,7 +105,7 @@ impl WalletManager {
                     _ => "https://eth.llamarpc.com"
                 });
             
-            info!("query区块高度，RPC: {}", rpc_url);
+            info!("query区块高度，RPC: {}", redact_url(rpc_url));
             
             // 创建Provider
             let provider = Provider::<Http>::try_from(rpc_url)
@@ -197,24 +206,37 @@ impl WalletManager {
         // ⚠️ 简化的固定费率模型（仅用于演示）
         // 真实环境需要query链上Gas费和桥接协议费率
         
-        let amount_f64 = amount.parse::<f64>().unwrap_or(0.0);
+        // 金额按 18 位小数精确解析，费用向下取整到最小单位
+        let amount = match Amount::parse(amount.trim(), 18, AssetTag::new(from_chain, "")) {
+            Ok(amount) => amount,
+            Err(e) => {
+                warn!("桥接金额无法解析 {:?}: {}", amount, e);
+                return "0".to_string();
+            }
+        };
         
-        // 基础费率：0.3%
-        let base_rate = 0.003;
+        // 基础费率：30 个基点（0.3%）
+        let base_rate_bps: u128 = 30;
         
-        // 简化的链特性调整（实际应query实时数据）
-        let chain_factor = match (from_chain, to_chain) {
-            ("eth", "polygon") | ("polygon", "eth") => 1.0,  // 低成本
-            ("eth", "bsc") | ("bsc", "eth") => 1.2,          // 中等成本
-            _ => 1.5,                                          // 其他链组合
+        // 简化的链特性调整，以十分之一为单位（实际应query实时数据）
+        let chain_factor_tenths: u128 = match (from_chain, to_chain) {
+            ("eth", "polygon") | ("polygon", "eth") => 10,  // 低成本
+            ("eth", "bsc") | ("bsc", "eth") => 12,          // 中等成本
+            _ => 15,                                          // 其他链组合
         };
         
-        let fee = amount_f64 * base_rate * chain_factor;
+        let fee = match amount.mul_div_floor(base_rate_bps * chain_factor_tenths, 10_000 * 10) {
+            Ok(fee) => fee.to_decimal_string(),
+            Err(e) => {
+                warn!("桥接费用计算failed: {}", e);
+                return "0".to_string();
+            }
+        };
         
-        info!("计算桥接费用: {} {} -> {} = {} (费率: {:.2}%)", 
-              amount, from_chain, to_chain, fee, base_rate * chain_factor * 100.0);
+        info!("计算桥接费用: {} {} -> {} = {} (费率: {} bps)", 
+              amount.to_decimal_string(), from_chain, to_chain, fee, base_rate_bps * chain_factor_tenths / 10);
 
-        fee.to_string()
+        fee
     }
 }
 
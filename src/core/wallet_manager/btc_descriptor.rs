@@ -0,0 +1,416 @@
+//! Bitcoin account xpub / output descriptor export
+//!
+//! External watch-only tools (Sparrow, mempool.space) track a wallet from its
+//! account-level extended public key. The account key is derived along the
+//! hardened path of the standard matching the address type:
+//!
+//! - Legacy  → BIP44 `m/44'/coin'/account'`, `pkh(...)`
+//! - SegWit  → BIP84 `m/84'/coin'/account'`, `wpkh(...)`
+//! - Taproot → BIP86 `m/86'/coin'/account'`, `tr(...)`
+//!
+//! The wallet's 32-byte master key is the BIP32 seed, the same convention the
+//! bip32 parity tests use. The xpriv only lives inside [`account_xpub`]; only
+//! the xpub leaves it and nothing here is written to storage.
+
+use bitcoin::bip32::{ChildNumber, Fingerprint, Xpriv, Xpub};
+use bitcoin::key::UntweakedPublicKey;
+use bitcoin::secp256k1::{Secp256k1, Signing, Verification};
+use bitcoin::{base58, Address, Network, PublicKey};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::WalletManager;
+use crate::blockchain::bitcoin::AddressType;
+use crate::core::errors::WalletError;
+use crate::core::wallet_info::WalletKeyKind;
+
+/// SLIP-132 version bytes for P2WPKH account keys (zpub / vpub)
+const ZPUB_VERSION_MAINNET: [u8; 4] = [0x04, 0xb2, 0x47, 0x46];
+const ZPUB_VERSION_TESTNET: [u8; 4] = [0x04, 0x5f, 0x1c, 0xf6];
+
+/// BIP380 descriptor character set; position in this string feeds the checksum
+const DESCRIPTOR_INPUT_CHARSET: &str =
+    "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+const DESCRIPTOR_CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Exported watch-only view of one Bitcoin account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BitcoinDescriptorExport {
+    /// `legacy`, `segwit` or `taproot`
+    pub address_type: String,
+    pub account: u32,
+    /// Master key fingerprint, lowercase hex
+    pub fingerprint: String,
+    /// e.g. `m/84'/0'/0'`
+    pub derivation_path: String,
+    /// Account xpub (tpub on test networks)
+    pub xpub: String,
+    /// SLIP-132 zpub/vpub form of the same key, SegWit only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zpub: Option<String>,
+    /// Receive chain descriptor with checksum, e.g. `wpkh([fp/84h/0h/0h]xpub.../0/*)#...`
+    pub descriptor: String,
+    /// Change chain descriptor with checksum
+    pub change_descriptor: String,
+}
+
+/// Parses the `type` accepted by the API and CLI
+pub fn parse_address_type(value: &str) -> Result<AddressType, WalletError> {
+    match value.to_ascii_lowercase().as_str() {
+        "legacy" | "p2pkh" => Ok(AddressType::Legacy),
+        "segwit" | "p2wpkh" => Ok(AddressType::SegWit),
+        "taproot" | "p2tr" => Ok(AddressType::Taproot),
+        other => Err(WalletError::ValidationError(format!(
+            "Unknown Bitcoin address type '{}' (expected legacy, segwit or taproot)",
+            other
+        ))),
+    }
+}
+
+fn address_type_name(address_type: AddressType) -> &'static str {
+    match address_type {
+        AddressType::Legacy => "legacy",
+        AddressType::SegWit => "segwit",
+        AddressType::Taproot => "taproot",
+    }
+}
+
+fn purpose(address_type: AddressType) -> u32 {
+    match address_type {
+        AddressType::Legacy => 44,
+        AddressType::SegWit => 84,
+        AddressType::Taproot => 86,
+    }
+}
+
+fn coin_type(network: Network) -> u32 {
+    if network == Network::Bitcoin {
+        0
+    } else {
+        1
+    }
+}
+
+fn hardened(index: u32) -> Result<ChildNumber, WalletError> {
+    ChildNumber::from_hardened_idx(index)
+        .map_err(|e| WalletError::ValidationError(format!("Invalid account index {}: {}", index, e)))
+}
+
+/// Derives the account xpub and the master fingerprint from `seed`.
+///
+/// The master and account xprivs are wiped before returning.
+fn account_xpub<C: Signing>(
+    secp: &Secp256k1<C>,
+    seed: &[u8],
+    address_type: AddressType,
+    account: u32,
+    network: Network,
+) -> Result<(Fingerprint, Xpub), WalletError> {
+    let path = [hardened(purpose(address_type))?, hardened(coin_type(network))?, hardened(account)?];
+
+    let mut master = Xpriv::new_master(network, seed)
+        .map_err(|e| WalletError::KeyDerivationError(format!("Invalid BIP32 seed: {}", e)))?;
+    let fingerprint = master.fingerprint(secp);
+    let derived = master.derive_priv(secp, &path);
+    master.private_key.non_secure_erase();
+
+    let mut account_key =
+        derived.map_err(|e| WalletError::KeyDerivationError(format!("Account derivation failed: {}", e)))?;
+    let xpub = Xpub::from_priv(secp, &account_key);
+    account_key.private_key.non_secure_erase();
+    Ok((fingerprint, xpub))
+}
+
+/// BIP380 descriptor checksum polymod step
+fn descriptor_polymod(c: u64, val: u64) -> u64 {
+    const GENERATOR: [u64; 5] = [0xf5dee51989, 0xa9fdca3312, 0x1bab10e32d, 0x3706b1677a, 0x644d626ffd];
+    let c0 = c >> 35;
+    let mut c = ((c & 0x7ffffffff) << 5) ^ val;
+    for (i, g) in GENERATOR.iter().enumerate() {
+        if (c0 >> i) & 1 == 1 {
+            c ^= g;
+        }
+    }
+    c
+}
+
+/// Computes the 8-character BIP380 checksum of a descriptor (without `#`).
+pub fn descriptor_checksum(descriptor: &str) -> Result<String, WalletError> {
+    let mut c = 1u64;
+    let mut cls = 0u64;
+    let mut cls_count = 0;
+    for ch in descriptor.chars() {
+        let pos = DESCRIPTOR_INPUT_CHARSET
+            .find(ch)
+            .ok_or_else(|| WalletError::ValidationError(format!("Invalid descriptor character '{}'", ch)))?
+            as u64;
+        c = descriptor_polymod(c, pos & 31);
+        cls = cls * 3 + (pos >> 5);
+        cls_count += 1;
+        if cls_count == 3 {
+            c = descriptor_polymod(c, cls);
+            cls = 0;
+            cls_count = 0;
+        }
+    }
+    if cls_count > 0 {
+        c = descriptor_polymod(c, cls);
+    }
+    for _ in 0..8 {
+        c = descriptor_polymod(c, 0);
+    }
+    c ^= 1;
+
+    Ok((0..8).map(|j| DESCRIPTOR_CHECKSUM_CHARSET[((c >> (5 * (7 - j))) & 31) as usize] as char).collect())
+}
+
+fn with_checksum(descriptor: String) -> Result<String, WalletError> {
+    let checksum = descriptor_checksum(&descriptor)?;
+    Ok(format!("{}#{}", descriptor, checksum))
+}
+
+/// Builds the xpub / descriptor export for one account of `seed`.
+pub fn bitcoin_descriptor_from_seed(
+    seed: &[u8],
+    address_type: AddressType,
+    account: u32,
+    network: Network,
+) -> Result<BitcoinDescriptorExport, WalletError> {
+    let secp = Secp256k1::new();
+    let (fingerprint, xpub) = account_xpub(&secp, seed, address_type, account, network)?;
+
+    let purpose = purpose(address_type);
+    let coin = coin_type(network);
+    let origin = format!("[{}/{}h/{}h/{}h]{}", fingerprint, purpose, coin, account, xpub);
+    let function = match address_type {
+        AddressType::Legacy => "pkh",
+        AddressType::SegWit => "wpkh",
+        AddressType::Taproot => "tr",
+    };
+
+    let zpub = (address_type == AddressType::SegWit).then(|| {
+        let mut encoded = xpub.encode();
+        let version = if network == Network::Bitcoin { ZPUB_VERSION_MAINNET } else { ZPUB_VERSION_TESTNET };
+        encoded[0..4].copy_from_slice(&version);
+        base58::encode_check(&encoded)
+    });
+
+    Ok(BitcoinDescriptorExport {
+        address_type: address_type_name(address_type).to_string(),
+        account,
+        fingerprint: fingerprint.to_string(),
+        derivation_path: format!("m/{}'/{}'/{}'", purpose, coin, account),
+        xpub: xpub.to_string(),
+        zpub,
+        descriptor: with_checksum(format!("{}({}/0/*)", function, origin))?,
+        change_descriptor: with_checksum(format!("{}({}/1/*)", function, origin))?,
+    })
+}
+
+/// Address of one child public key; taproot uses the key as the BIP86 internal key
+fn address_for_key<C: Verification>(
+    secp: &Secp256k1<C>,
+    key: &Xpub,
+    address_type: AddressType,
+    network: Network,
+) -> Result<String, WalletError> {
+    let address = match address_type {
+        AddressType::Legacy => Address::p2pkh(&PublicKey::new(key.public_key), network),
+        AddressType::SegWit => Address::p2wpkh(&PublicKey::new(key.public_key), network)
+            .map_err(|e| WalletError::AddressGenerationFailed(e.to_string()))?,
+        AddressType::Taproot => Address::p2tr(secp, UntweakedPublicKey::from(key.public_key), None, network),
+    };
+    Ok(address.to_string())
+}
+
+/// Receive addresses `account/0/start .. account/0/start+count` of `seed`.
+pub fn derive_addresses(
+    seed: &[u8],
+    address_type: AddressType,
+    account: u32,
+    network: Network,
+    start: u32,
+    count: u32,
+) -> Result<Vec<String>, WalletError> {
+    let secp = Secp256k1::new();
+    let (_, xpub) = account_xpub(&secp, seed, address_type, account, network)?;
+
+    (start..start.saturating_add(count))
+        .map(|index| {
+            let child = ChildNumber::from_normal_idx(index)
+                .map_err(|e| WalletError::ValidationError(format!("Invalid address index {}: {}", index, e)))?;
+            let key = xpub
+                .derive_pub(&secp, &[ChildNumber::Normal { index: 0 }, child])
+                .map_err(|e| WalletError::KeyDerivationError(e.to_string()))?;
+            address_for_key(&secp, &key, address_type, network)
+        })
+        .collect()
+}
+
+impl WalletManager {
+    /// Exports the account xpub and output descriptors of a Bitcoin wallet.
+    ///
+    /// Only HD wallets have a key tree; imported single-key wallets are
+    /// rejected with [`WalletError::UnsupportedWalletKind`].
+    pub async fn export_bitcoin_descriptor(
+        &self,
+        name: &str,
+        password: &str,
+        address_type: AddressType,
+        account: u32,
+    ) -> Result<BitcoinDescriptorExport, WalletError> {
+        let wallet = self
+            .get_wallet_by_name(name)
+            .await?
+            .ok_or_else(|| WalletError::NotFoundError(format!("Wallet '{}' not found", name)))?;
+        if wallet.key_kind != WalletKeyKind::Hd {
+            return Err(WalletError::UnsupportedWalletKind(format!(
+                "Wallet '{}' holds a single imported key and has no extended public key",
+                name
+            )));
+        }
+
+        let master_key = self.decrypt_master_key(&wallet, password).await?;
+        let export = bitcoin_descriptor_from_seed(&master_key, address_type, account, Network::Bitcoin)?;
+        info!(
+            "Exported {} descriptor for wallet '{}' (account {})",
+            export.address_type, name, account
+        );
+        Ok(export)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const MNEMONIC: &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn seed() -> [u8; 64] {
+        bip39::Mnemonic::parse(MNEMONIC).unwrap().to_seed("")
+    }
+
+    #[test]
+    fn test_checksum_reference_vector() {
+        // BIP380 example
+        assert_eq!(descriptor_checksum("raw(deadbeef)").unwrap(), "89f8spxm");
+        assert!(descriptor_checksum("raw(\u{e9})").is_err());
+    }
+
+    #[test]
+    fn test_bip84_vector() {
+        let export = bitcoin_descriptor_from_seed(&seed(), AddressType::SegWit, 0, Network::Bitcoin).unwrap();
+        let xpub = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+        assert_eq!(export.fingerprint, "73c5da0a");
+        assert_eq!(export.derivation_path, "m/84'/0'/0'");
+        assert_eq!(export.xpub, xpub);
+        assert_eq!(
+            export.zpub.as_deref(),
+            Some("zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs")
+        );
+        assert_eq!(export.descriptor, format!("wpkh([73c5da0a/84h/0h/0h]{}/0/*)#afwvtk2s", xpub));
+        assert_eq!(export.change_descriptor, format!("wpkh([73c5da0a/84h/0h/0h]{}/1/*)#vatdkr6g", xpub));
+    }
+
+    #[test]
+    fn test_bip86_vector() {
+        let export = bitcoin_descriptor_from_seed(&seed(), AddressType::Taproot, 0, Network::Bitcoin).unwrap();
+        let xpub = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+        assert_eq!(export.xpub, xpub);
+        assert_eq!(export.zpub, None);
+        assert_eq!(export.descriptor, format!("tr([73c5da0a/86h/0h/0h]{}/0/*)#se42yddx", xpub));
+
+        let addresses = derive_addresses(&seed(), AddressType::Taproot, 0, Network::Bitcoin, 0, 1).unwrap();
+        assert_eq!(addresses[0], "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+    }
+
+    #[test]
+    fn test_bip44_descriptor() {
+        let export = bitcoin_descriptor_from_seed(&seed(), AddressType::Legacy, 0, Network::Bitcoin).unwrap();
+        assert!(export.descriptor.starts_with("pkh([73c5da0a/44h/0h/0h]xpub6BosfCnifzxcFwrSzQiqu2DBVTshkC"));
+        assert!(export.descriptor.ends_with("/0/*)#5l2aanww"));
+    }
+
+    #[test]
+    fn test_descriptor_addresses_match_derive_addresses() {
+        // 由外部工具（BIP84/86 参考实现）从 descriptor 推导出的前 5 个接收address
+        let external = [
+            (
+                AddressType::SegWit,
+                [
+                    "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+                    "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g",
+                    "bc1qp59yckz4ae5c4efgw2s5wfyvrz0ala7rgvuz8z",
+                    "bc1qgl5vlg0zdl7yvprgxj9fevsc6q6x5dmcyk3cn3",
+                    "bc1qm97vqzgj934vnaq9s53ynkyf9dgr05rargr04n",
+                ],
+            ),
+            (
+                AddressType::Taproot,
+                [
+                    "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+                    "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh",
+                    "bc1p0d0rhyynq0awa9m8cqrcr8f5nxqx3aw29w4ru5u9my3h0sfygnzs9khxz8",
+                    "bc1py0vryk8aqusz65yzuudypggvswzkcpwtau8q0sjm0stctwup0xlqkkxler",
+                    "bc1pjpp8nwqvhkx6kdna6vpujdqglvz2304twfd308ve5ppyxpmcjufs7k6xyr",
+                ],
+            ),
+            (
+                AddressType::Legacy,
+                [
+                    "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA",
+                    "1Ak8PffB2meyfYnbXZR9EGfLfFZVpzJvQP",
+                    "1MNF5RSaabFwcbtJirJwKnDytsXXEsVsNb",
+                    "1MVGa13XFvvpKGZdX389iU8b3qwtmAyrsJ",
+                    "1Gka4JdwhLxRwXaC6oLNH4YuEogeeSwqW7",
+                ],
+            ),
+        ];
+        let secp = Secp256k1::new();
+        for (address_type, expected) in external {
+            let ours = derive_addresses(&seed(), address_type, 0, Network::Bitcoin, 0, 5).unwrap();
+            assert_eq!(ours, expected, "{:?}", address_type);
+
+            // 只用 descriptor 里的 xpub 做公钥派生，同样得到这些address
+            let export = bitcoin_descriptor_from_seed(&seed(), address_type, 0, Network::Bitcoin).unwrap();
+            let xpub = Xpub::from_str(&export.xpub).unwrap();
+            for (index, address) in expected.iter().enumerate() {
+                let path = [ChildNumber::Normal { index: 0 }, ChildNumber::Normal { index: index as u32 }];
+                let child = xpub.derive_pub(&secp, &path).unwrap();
+                assert_eq!(&address_for_key(&secp, &child, address_type, Network::Bitcoin).unwrap(), address);
+            }
+        }
+    }
+
+    #[test]
+    fn test_account_and_network_change_path() {
+        let account1 = bitcoin_descriptor_from_seed(&seed(), AddressType::SegWit, 1, Network::Bitcoin).unwrap();
+        assert_eq!(account1.derivation_path, "m/84'/0'/1'");
+        assert!(account1.descriptor.starts_with("wpkh([73c5da0a/84h/0h/1h]xpub"));
+
+        let testnet = bitcoin_descriptor_from_seed(&seed(), AddressType::SegWit, 0, Network::Testnet).unwrap();
+        assert_eq!(testnet.derivation_path, "m/84'/1'/0'");
+        assert!(testnet.xpub.starts_with("tpub"));
+        assert!(testnet.zpub.unwrap().starts_with("vpub"));
+
+        assert!(bitcoin_descriptor_from_seed(&seed(), AddressType::SegWit, 1 << 31, Network::Bitcoin).is_err());
+    }
+
+    #[test]
+    fn test_export_never_contains_private_key() {
+        let export = bitcoin_descriptor_from_seed(&seed(), AddressType::SegWit, 0, Network::Bitcoin).unwrap();
+        let json = serde_json::to_string(&export).unwrap();
+        assert!(!json.contains("xprv"));
+        assert!(!json.contains("zprv"));
+    }
+
+    #[test]
+    fn test_parse_address_type() {
+        assert_eq!(parse_address_type("SegWit").unwrap(), AddressType::SegWit);
+        assert_eq!(parse_address_type("p2tr").unwrap(), AddressType::Taproot);
+        assert_eq!(parse_address_type("legacy").unwrap(), AddressType::Legacy);
+        assert!(matches!(parse_address_type("p2sh"), Err(WalletError::ValidationError(_))));
+    }
+}
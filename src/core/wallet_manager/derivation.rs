@@ -12,8 +12,16 @@
 //! - Private key仅在内存中临时存在
 //! - 使用zeroizeClear敏感数据
 //! - 符合BIP32/BIP39/BIP44标准
+//!
+//! ## 自定义路径
+//! [`DerivationPath`] 是校验过的 BIP44 路径（如 `m/44'/60'/0'/0/5`）。wallet只保存
+//! 32 字节主密钥，设置了路径的wallet以主密钥为 BIP32 种子派生该路径下的子密钥
+//! （与 BTC descriptor 导出相同）；未设置路径的wallet直接使用主密钥。
 
 use crate::core::errors::WalletError;
+use crate::security::SecretVec;
+use std::fmt;
+use std::str::FromStr;
 use tracing::{debug, info};
 
 /// BIP44 derivation path常量
@@ -31,6 +39,121 @@ pub mod paths {
     pub const BSC: &str = "m/44'/60'/0'/0/0";
 }
 
+/// BIP32 hardened 标志位（`'` / `h`）
+pub const HARDENED: u32 = 0x8000_0000;
+
+/// 路径最多的层数
+pub const MAX_PATH_DEPTH: usize = 10;
+
+/// BIP44 中必须 hardened 的前三层：purpose / coin_type / account
+const HARDENED_LEVELS: [&str; 3] = ["purpose", "coin type", "account"];
+
+/// 校验过的 BIP32/BIP44 派生路径
+///
+/// 格式为 `m/44'/60'/0'/0/5`：hardened 层以 `'`（或 `h`/`H`）结尾，
+/// purpose、coin type、account 三层必须 hardened，每层索引小于 2^31，
+/// 不带前导零。[`Display`](fmt::Display) 输出统一用 `'` 的规范形式。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// 解析并校验路径字符串
+    pub fn parse(path: &str) -> Result<Self, WalletError> {
+        let invalid =
+            |reason: String| WalletError::ValidationError(format!("Invalid derivation path '{}': {}", path, reason));
+
+        let mut segments = path.split('/');
+        if segments.next() != Some("m") {
+            return Err(invalid("must start with 'm/'".to_string()));
+        }
+        let mut indexes = Vec::new();
+        for (level, segment) in segments.enumerate() {
+            let (digits, hardened) = match segment.strip_suffix(['\'', 'h', 'H']) {
+                Some(digits) => (digits, true),
+                None => (segment, false),
+            };
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid(format!("segment {} ('{}') is not a number", level + 1, segment)));
+            }
+            if digits.len() > 1 && digits.starts_with('0') {
+                return Err(invalid(format!("segment {} ('{}') has a leading zero", level + 1, segment)));
+            }
+            let index = match digits.parse::<u32>() {
+                Ok(index) if index < HARDENED => index,
+                _ => return Err(invalid(format!("index {} must be below 2^31", digits))),
+            };
+            if let Some(name) = HARDENED_LEVELS.get(level) {
+                if !hardened {
+                    return Err(invalid(format!("{} level ({}) must be hardened", name, segment)));
+                }
+            }
+            indexes.push(if hardened { index | HARDENED } else { index });
+        }
+        if indexes.len() < HARDENED_LEVELS.len() {
+            return Err(invalid("purpose, coin type and account levels are required".to_string()));
+        }
+        if indexes.len() > MAX_PATH_DEPTH {
+            return Err(invalid(format!("at most {} levels", MAX_PATH_DEPTH)));
+        }
+        Ok(Self(indexes))
+    }
+
+    /// 各层子索引（hardened 层已带 [`HARDENED`] 位）
+    pub fn indexes(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// 以 `seed` 为 BIP32 种子派生该路径下的 secp256k1 Private key
+    pub fn derive_key(&self, seed: &[u8]) -> Result<SecretVec, WalletError> {
+        use coins_bip32::xkeys::{Parent, XPriv};
+
+        let mut xpriv = XPriv::root_from_seed(seed, None)
+            .map_err(|e| WalletError::KeyDerivationError(format!("Invalid BIP32 seed: {}", e)))?;
+        for &index in &self.0 {
+            xpriv = xpriv
+                .derive_child(index)
+                .map_err(|e| WalletError::KeyDerivationError(format!("Failed to derive {}: {}", self, e)))?;
+        }
+        let key: &k256::ecdsa::SigningKey = xpriv.as_ref();
+        Ok(SecretVec::new(key.to_bytes().to_vec()))
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("m")?;
+        for &index in &self.0 {
+            match index & HARDENED {
+                0 => write!(f, "/{}", index)?,
+                _ => write!(f, "/{}'", index & !HARDENED)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// 以规范字符串形式序列化，反序列化时重新校验
+impl serde::Serialize for DerivationPath {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for DerivationPath {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Self::parse(&raw).map_err(serde::de::Error::custom)
+    }
+}
+
 /// frommnemonic推导Ethereumaddress
 ///
 /// # Arguments
@@ -295,5 +418,119 @@ mod tests {
         assert_eq!(get_derivation_path("btc", 0), "m/44'/0'/0'/0/0");
         assert_eq!(get_derivation_path("eth", 5), "m/44'/60'/0'/0/5");
     }
+
+    fn test_seed() -> Vec<u8> {
+        bip39::Mnemonic::parse(TEST_MNEMONIC).unwrap().to_seed("").to_vec()
+    }
+
+    #[test]
+    fn test_derivation_path_parse_and_display() {
+        let path = DerivationPath::parse("m/44'/60'/0'/0/5").unwrap();
+        assert_eq!(path.indexes(), &[44 | HARDENED, 60 | HARDENED, HARDENED, 0, 5]);
+        assert_eq!(path.to_string(), "m/44'/60'/0'/0/5");
+        // h / H 记法规范化为 '
+        assert_eq!("m/44h/60H/2'/1/7".parse::<DerivationPath>().unwrap().to_string(), "m/44'/60'/2'/1/7");
+        assert_eq!(DerivationPath::parse("m/44'/60'/0'").unwrap().indexes().len(), 3);
+        let max = format!("m/44'/60'/{}'/0/{}", HARDENED - 1, HARDENED - 1);
+        assert_eq!(DerivationPath::parse(&max).unwrap().to_string(), max);
+
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(json, "\"m/44'/60'/0'/0/5\"");
+        assert_eq!(serde_json::from_str::<DerivationPath>(&json).unwrap(), path);
+        assert!(serde_json::from_str::<DerivationPath>("\"m/44/60'/0'\"").is_err());
+    }
+
+    #[test]
+    fn test_derivation_path_rejects_malformed() {
+        let rejected = [
+            "",
+            "m",
+            "m/",
+            "44'/60'/0'/0/0",
+            "M/44'/60'/0'/0/0",
+            "m/44'/60'/0'//0",
+            "m/44'/60'/0'/0/",
+            "m/44'/60'/0'/x/0",
+            "m/44'/60'/0'/-1/0",
+            "m/44'/60'/0'/+1/0",
+            "m/44'/60'/0'/0/05",
+            "m/44''/60'/0'/0/0",
+            "m/44'/60'/0'/0 /0",
+            "m/44'/60'",
+            "m/44'/60'/0'/0/0/0/0/0/0/0/0",
+        ];
+        for path in rejected {
+            let err = DerivationPath::parse(path).unwrap_err();
+            assert!(matches!(err, WalletError::ValidationError(_)), "{} -> {:?}", path, err);
+        }
+    }
+
+    #[test]
+    fn test_derivation_path_requires_hardened_levels() {
+        for path in ["m/44/60'/0'/0/0", "m/44'/60/0'/0/0", "m/44'/60'/0/0/0"] {
+            let err = DerivationPath::parse(path).unwrap_err().to_string();
+            assert!(err.contains("must be hardened"), "{}: {}", path, err);
+        }
+        // change / index 层可以 hardened
+        assert!(DerivationPath::parse("m/44'/60'/0'/0'/0'").is_ok());
+    }
+
+    #[test]
+    fn test_derivation_path_rejects_index_overflow() {
+        for path in ["m/44'/60'/0'/0/2147483648", "m/44'/60'/2147483648'/0/0", "m/44'/60'/0'/0/99999999999"] {
+            let err = DerivationPath::parse(path).unwrap_err().to_string();
+            assert!(err.contains("below 2^31"), "{}: {}", path, err);
+        }
+    }
+
+    /// BIP44 secp256k1 参考向量（TEST_MNEMONIC，空 passphrase）
+    #[test]
+    #[cfg(feature = "ethereum")]
+    fn test_derive_key_known_vectors() {
+        use super::super::master_key_derivation::derive_ethereum_address_from_key;
+
+        let vectors = [
+            (
+                "m/44'/60'/0'/0/0",
+                "1ab42cc412b618bdea3a599e3c9bae199ebf030895b039e9db1e30dafb12b727",
+                "0x9858effd232b4033e47d90003d41ec34ecaeda94",
+            ),
+            (
+                "m/44'/60'/0'/0/1",
+                "9a983cb3d832fbde5ab49d692b7a8bf5b5d232479c99333d0fc8e1d21f1b55b6",
+                "0x6fac4d18c912343bf86fa7049364dd4e424ab9c0",
+            ),
+            (
+                "m/44'/60'/0'/0/5",
+                "5a8787e6b7e11a74a22ee97b8164c7d69cd5668c6065bbfbc87e6a34a24b135c",
+                "0xa40cfbfc8534ffc84e20a7d8bbc3729b26a35f6f",
+            ),
+        ];
+        let seed = test_seed();
+        for (path, private_key, address) in vectors {
+            let key = DerivationPath::parse(path).unwrap().derive_key(&seed).unwrap();
+            assert_eq!(hex::encode(&*key), private_key, "{}", path);
+            assert_eq!(derive_ethereum_address_from_key(&key).unwrap().to_lowercase(), address, "{}", path);
+        }
+
+        let account = DerivationPath::parse("m/44'/60'/1'/0/0").unwrap().derive_key(&seed).unwrap();
+        let address = derive_ethereum_address_from_key(&account).unwrap();
+        assert_eq!(address.to_lowercase(), "0x78839f6054d7ed13918bae0473ba31b1ca9d7265");
+    }
+
+    /// 与基于mnemonic的 ethers 派生结果一致
+    #[test]
+    #[cfg(feature = "ethereum")]
+    fn test_derive_key_matches_mnemonic_derivation() {
+        use super::super::master_key_derivation::derive_ethereum_address_from_key;
+
+        let seed = test_seed();
+        for index in [0, 1, 5] {
+            let path = DerivationPath::parse(&get_derivation_path("eth", index)).unwrap();
+            let from_path = derive_ethereum_address_from_key(&path.derive_key(&seed).unwrap()).unwrap();
+            let from_mnemonic = derive_ethereum_address(TEST_MNEMONIC, index).unwrap();
+            assert_eq!(from_path.to_lowercase(), from_mnemonic.to_lowercase());
+        }
+    }
 }
 
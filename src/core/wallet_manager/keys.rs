@@ -4,6 +4,7 @@
 
 use super::WalletManager;
 use crate::core::errors::WalletError;
+use crate::security::memory_protection::TaintedBytes;
 use crate::security::SecretVec;
 use tracing::info;
 
@@ -25,15 +26,16 @@ impl WalletManager {
     /// * `mnemonic` - mnemonic字符串
     ///
     /// # Returns
-    /// 派生的主密钥（Zeroizing）
+    /// 派生的主密钥（Zeroizing；复制到普通 `Vec<u8>` 会被泄漏审计记录）
     ///
     /// # Errors
     /// 返回`WalletError::KeyDerivationError`如果派生failed
     pub async fn derive_master_key(
         &self,
         mnemonic: &str,
-    ) -> Result<SecretVec, WalletError> {
-        crate::core::wallet::create::derive_master_key(mnemonic).await
+    ) -> Result<TaintedBytes, WalletError> {
+        let master_key = crate::core::wallet::create::derive_master_key(mnemonic).await?;
+        Ok(TaintedBytes::new(master_key, "mnemonic master key"))
     }
 
     /// 测试用：派生Private key
@@ -0,0 +1,384 @@
+//! Keystore V3 import/export
+//!
+//! An imported key becomes a [`WalletKeyKind::ImportedKey`] wallet. The key
+//! is re-encrypted under the wallet password exactly like an HD master key,
+//! so address derivation and signing need no special casing.
+//!
+//! The enveloped variants wrap the same keystore in a signed
+//! [`KeystoreEnvelope`] for exchange with partner systems; the envelope is
+//! authenticated before the keystore is touched.
+//!
+//! [`export_wallet`](WalletManager::export_wallet) / [`import_wallet`](WalletManager::import_wallet)
+//! move a whole wallet between instances as a [`WalletKeystore`]: the key
+//! keeps its kind and the wallet keeps its networks and threshold.
+
+use super::master_key_derivation::derive_ethereum_address_from_key;
+use super::WalletManager;
+use crate::core::errors::WalletError;
+use crate::core::wallet_info::{SecureWalletData, WalletKeyKind};
+use crate::crypto::keystore_envelope::{EnvelopeError, EnvelopeMeta, KeystoreEnvelope};
+use crate::crypto::keystore_v3::KeystoreV3;
+use crate::crypto::wallet_keystore::{WalletKeystore, WalletKeystoreMeta};
+use ethers::core::k256::ecdsa::SigningKey;
+use ethers::types::Address;
+use ethers::utils::to_checksum;
+use crate::security::memory_protection::TaintedBytes;
+use crate::security::password_validator::{validate_password, PasswordPolicy};
+use crate::storage::WalletStorageTrait;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Result of [`WalletManager::import_enveloped_keystore`]
+#[derive(Debug, Clone)]
+pub struct EnvelopeImport {
+    /// Ethereum address (0x...) of the imported key
+    pub address: String,
+    pub meta: EnvelopeMeta,
+    /// Compressed transport key that signed the envelope, hex
+    pub sender: String,
+}
+
+impl WalletManager {
+    /// Import a wallet from an Ethereum keystore V3 JSON document
+    ///
+    /// # Arguments
+    /// * `name` - Wallet name (must be unique)
+    /// * `keystore_json` - Keystore file contents (scrypt or pbkdf2)
+    /// * `keystore_password` - Password of the keystore file; only used to decrypt it
+    /// * `wallet_password` - Password the key is stored under from now on
+    ///
+    /// # Returns
+    /// * `Ok(String)` - Ethereum address (0x...) of the imported key
+    ///
+    /// # Errors
+    /// * `WalletError::DecryptionError` - Keystore MAC mismatch (wrong password)
+    /// * `WalletError::DeserializationError` / `ValidationError` - Malformed or unsupported keystore
+    /// * `WalletError::ValidationError` - Wallet exists or keystore address mismatch
+    /// * `WalletError::SecurityError` - Wallet password fails the password policy
+    pub async fn import_keystore_v3(
+        &self,
+        name: &str,
+        keystore_json: &str,
+        keystore_password: &str,
+        wallet_password: &str,
+    ) -> Result<String, WalletError> {
+        info!("Importing keystore V3 as wallet: {}", name);
+
+        // decrypt_master_key enforces the same policy, so reject early
+        validate_password(wallet_password, &PasswordPolicy::default())?;
+        if self.wallets.read().contains_key(name) {
+            return Err(WalletError::ValidationError(format!("Wallet '{}' already exists", name)));
+        }
+
+        let keystore = KeystoreV3::from_json(keystore_json)?;
+        let private_key = TaintedBytes::new(keystore.decrypt(keystore_password)?, "imported keystore key");
+        if private_key.len() != 32 {
+            return Err(WalletError::InvalidPrivateKey(format!(
+                "keystore key is {} bytes, expected 32",
+                private_key.len()
+            )));
+        }
+        // rejects zero / out-of-range scalars
+        let address = derive_ethereum_address_from_key(&private_key)
+            .map_err(|e| WalletError::InvalidPrivateKey(e.to_string()))?;
+        if !keystore.address_matches(&address) {
+            return Err(WalletError::ValidationError(
+                "keystore address does not match the decrypted key".into(),
+            ));
+        }
+
+        let (encrypted_master_key, salt, nonce) = self.encrypt_master_key(&private_key, wallet_password)?;
+        let mut info = self.new_wallet_info(name, false);
+        info.multi_sig_threshold = 1;
+        info.networks = vec!["eth".to_string()];
+        let wallet_data = SecureWalletData {
+            info,
+            encrypted_master_key,
+            shamir_shares: Vec::new(),
+            salt,
+            nonce,
+            schema_version: SecureWalletData::default_schema_version(),
+            kek_id: None,
+            key_kind: WalletKeyKind::ImportedKey,
+            passphrase_protected: false,
+        };
+
+        {
+            let mut wallets = self.wallets.write();
+            // re-check under the write lock: a concurrent import may have won
+            if wallets.contains_key(name) {
+                return Err(WalletError::ValidationError(format!("Wallet '{}' already exists", name)));
+            }
+            wallets.insert(name.to_string(), wallet_data);
+        }
+
+        info!("✅ Imported keystore wallet '{}' ({})", name, address);
+        Ok(address)
+    }
+
+    /// Export a wallet's key as an Ethereum keystore V3 document
+    ///
+    /// Uses scrypt with `security.keystore_scrypt_n` and a fresh salt, IV and UUID.
+    /// For a wallet with a derivation path the exported key is the child key
+    /// the wallet signs with, so other tools see the same address.
+    ///
+    /// # Arguments
+    /// * `name` - Wallet name
+    /// * `wallet_password` - Current wallet password
+    /// * `export_password` - Password protecting the exported keystore
+    pub async fn export_keystore_v3(
+        &self,
+        name: &str,
+        wallet_password: &str,
+        export_password: &str,
+    ) -> Result<KeystoreV3, WalletError> {
+        info!("Exporting wallet '{}' as keystore V3", name);
+
+        // the exported file is as sensitive as the key itself
+        validate_password(export_password, &PasswordPolicy::default())?;
+
+        let wallet = self
+            .get_wallet_by_name(name)
+            .await?
+            .ok_or_else(|| WalletError::NotFoundError(format!("Wallet '{}' not found", name)))?;
+        let private_key = self.decrypt_signing_key(&wallet, wallet_password).await?;
+        let address = derive_ethereum_address_from_key(&private_key)?;
+
+        KeystoreV3::encrypt(
+            &private_key,
+            export_password,
+            &address,
+            self.config.security.keystore_scrypt_n,
+        )
+    }
+
+    /// Export a wallet as a versioned wallet keystore JSON document
+    ///
+    /// The key is re-encrypted under `password` with argon2id
+    /// (`security.wallet_keystore_m_cost`); the document never contains a
+    /// mnemonic, only the derived key.
+    ///
+    /// # Arguments
+    /// * `name` - Wallet name
+    /// * `password` - Wallet password; also protects the exported keystore
+    ///
+    /// # Errors
+    /// * `WalletError::NotFoundError` - No such wallet
+    /// * `WalletError::CryptoError` - Wrong wallet password
+    pub async fn export_wallet(&self, name: &str, password: &str) -> Result<String, WalletError> {
+        info!("Exporting wallet '{}' as wallet keystore", name);
+
+        let wallet = self
+            .get_wallet_by_name(name)
+            .await?
+            .ok_or_else(|| WalletError::NotFoundError(format!("Wallet '{}' not found", name)))?;
+        let key = self.decrypt_master_key(&wallet, password).await?;
+        let meta = WalletKeystoreMeta {
+            name: wallet.info.name.clone(),
+            key_kind: wallet.key_kind,
+            quantum_safe: wallet.info.quantum_safe,
+            multi_sig_threshold: wallet.info.multi_sig_threshold,
+            networks: wallet.info.networks.clone(),
+            allowed_networks: wallet.info.allowed_networks.clone(),
+            derivation_path: wallet.info.derivation_path.clone(),
+            address: derive_ethereum_address_from_key(&key)?,
+        };
+
+        WalletKeystore::encrypt(&key, password, meta, self.config.security.wallet_keystore_m_cost)?.to_json()
+    }
+
+    /// Import a wallet keystore produced by [`export_wallet`](Self::export_wallet)
+    ///
+    /// The MAC is verified before decryption; the key is then stored under
+    /// `password` exactly like a locally created wallet and persisted through
+    /// `storage`.
+    ///
+    /// # Returns
+    /// * `Ok(String)` - Ethereum address (0x...) of the imported wallet
+    ///
+    /// # Errors
+    /// * `WalletError::InvalidPassword` - Keystore MAC mismatch (wrong password or tampered file)
+    /// * `WalletError::DeserializationError` / `ValidationError` - Malformed or unsupported keystore
+    /// * `WalletError::ValidationError` - Wallet exists or keystore address mismatch
+    /// * `WalletError::SecurityError` - Password fails the password policy
+    /// * `WalletError::StorageError` - Persisting the wallet failed; nothing is kept in memory
+    pub async fn import_wallet(
+        &self,
+        name: &str,
+        keystore_json: &str,
+        password: &str,
+        storage: &Arc<dyn WalletStorageTrait + Send + Sync>,
+    ) -> Result<String, WalletError> {
+        info!("Importing wallet keystore as wallet: {}", name);
+
+        validate_password(password, &PasswordPolicy::default())?;
+        if self.wallets.read().contains_key(name) {
+            return Err(WalletError::ValidationError(format!("Wallet '{}' already exists", name)));
+        }
+
+        let keystore = WalletKeystore::from_json(keystore_json)?;
+        let key = TaintedBytes::new(keystore.decrypt(password)?, "imported wallet key");
+        if key.len() != 32 {
+            return Err(WalletError::InvalidPrivateKey(format!("keystore key is {} bytes, expected 32", key.len())));
+        }
+        let address =
+            derive_ethereum_address_from_key(&key).map_err(|e| WalletError::InvalidPrivateKey(e.to_string()))?;
+        if !address.eq_ignore_ascii_case(&keystore.wallet.address) {
+            return Err(WalletError::ValidationError("keystore address does not match the decrypted key".into()));
+        }
+
+        let meta = keystore.wallet;
+        let (encrypted_master_key, salt, nonce) = self.encrypt_master_key(&key, password)?;
+        let mut info = self.new_wallet_info(name, meta.quantum_safe);
+        info.multi_sig_threshold = meta.multi_sig_threshold;
+        info.networks = meta.networks;
+        info.allowed_networks = meta.allowed_networks;
+        info.derivation_path = meta.derivation_path;
+        let wallet_data = SecureWalletData {
+            info,
+            encrypted_master_key,
+            shamir_shares: Vec::new(),
+            salt,
+            nonce,
+            schema_version: SecureWalletData::default_schema_version(),
+            kek_id: None,
+            key_kind: meta.key_kind,
+            passphrase_protected: false,
+        };
+        let serialized =
+            bincode::serialize(&wallet_data).map_err(|e| WalletError::SerializationError(e.to_string()))?;
+
+        {
+            let mut wallets = self.wallets.write();
+            if wallets.contains_key(name) {
+                return Err(WalletError::ValidationError(format!("Wallet '{}' already exists", name)));
+            }
+            wallets.insert(name.to_string(), wallet_data);
+        }
+        if let Err(e) = storage.store_wallet(name, &serialized, meta.quantum_safe).await {
+            warn!("failed to persist imported wallet {}: {}", name, e);
+            self.wallets.write().remove(name);
+            return Err(WalletError::StorageError(e.to_string()));
+        }
+
+        info!("✅ Imported wallet keystore as '{}' ({})", name, address);
+        Ok(address)
+    }
+
+    /// Import a wallet from a signed keystore envelope
+    ///
+    /// The envelope must be signed by `expected_sender` (SEC1 public key, hex)
+    /// and verify before the inner keystore is decrypted; the import itself is
+    /// [`import_keystore_v3`](Self::import_keystore_v3), which also checks the
+    /// decrypted key against `meta.address`.
+    ///
+    /// # Errors
+    /// * `EnvelopeError::SenderMismatch` - Signed by a different transport key
+    /// * `EnvelopeError::BadSignature` - Envelope altered after signing
+    /// * `EnvelopeError::MissingMeta` - Required `meta` field absent
+    /// * `EnvelopeError::Wallet` - Any error of the keystore V3 import
+    pub async fn import_enveloped_keystore(
+        &self,
+        name: &str,
+        envelope_json: &str,
+        keystore_password: &str,
+        wallet_password: &str,
+        expected_sender: &str,
+    ) -> Result<EnvelopeImport, EnvelopeError> {
+        let opened = KeystoreEnvelope::open(envelope_json, expected_sender)?;
+        info!("Importing keystore envelope from {} ({}) as wallet: {}", opened.sender, opened.meta.tool, name);
+
+        let address = self
+            .import_keystore_v3(name, &opened.keystore.to_json()?, keystore_password, wallet_password)
+            .await?;
+        Ok(EnvelopeImport { address, meta: opened.meta, sender: opened.sender })
+    }
+
+    /// Export a wallet's key as a keystore envelope signed by `transport_key`
+    ///
+    /// The inner keystore is exactly what [`export_keystore_v3`](Self::export_keystore_v3)
+    /// produces; `label` is carried in `meta` unchanged.
+    pub async fn export_enveloped_keystore(
+        &self,
+        name: &str,
+        wallet_password: &str,
+        export_password: &str,
+        label: Option<&str>,
+        transport_key: &SigningKey,
+    ) -> Result<KeystoreEnvelope, EnvelopeError> {
+        let keystore = self.export_keystore_v3(name, wallet_password, export_password).await?;
+        let wallet = self
+            .get_wallet_by_name(name)
+            .await?
+            .ok_or_else(|| WalletError::NotFoundError(format!("Wallet '{}' not found", name)))?;
+        let address = keystore
+            .address
+            .as_deref()
+            .and_then(|a| a.parse::<Address>().ok())
+            .map(|a| to_checksum(&a, None))
+            .ok_or_else(|| WalletError::SerializationError("exported keystore has no address".into()))?;
+        let meta = EnvelopeMeta {
+            tool: format!("defi-hot-wallet/{}", env!("CARGO_PKG_VERSION")),
+            origin: match wallet.key_kind {
+                WalletKeyKind::Hd => "bip32-master",
+                WalletKeyKind::ImportedKey => "imported",
+            }
+            .to_string(),
+            address,
+            label: label.map(str::to_string),
+        };
+        Ok(KeystoreEnvelope::seal(keystore, meta, transport_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::WalletConfig;
+
+    const WALLET_PASSWORD: &str = "Imp0rtedWallet";
+
+    async fn manager() -> WalletManager {
+        let mut config = WalletConfig::default();
+        config.security.keystore_scrypt_n = 1024;
+        config.security.pbkdf2_iterations = 1_000;
+        WalletManager::new(&config).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_keeps_address_and_kind() {
+        let wm = manager().await;
+        wm.create_wallet("hd_source", WALLET_PASSWORD, false).await.unwrap();
+        let source_address = wm.get_ethereum_address_from_master_key("hd_source", WALLET_PASSWORD).await.unwrap();
+
+        let ks = wm.export_keystore_v3("hd_source", WALLET_PASSWORD, "Exp0rtSecret9").await.unwrap();
+        assert_eq!(ks.address.as_deref(), Some(source_address.trim_start_matches("0x").to_lowercase().as_str()));
+
+        let address = wm
+            .import_keystore_v3("imported", &ks.to_json().unwrap(), "Exp0rtSecret9", WALLET_PASSWORD)
+            .await
+            .unwrap();
+        assert_eq!(address, source_address);
+
+        let imported = wm.get_wallet_by_name("imported").await.unwrap().unwrap();
+        assert_eq!(imported.key_kind, WalletKeyKind::ImportedKey);
+        assert_eq!(wm.get_ethereum_address_from_master_key("imported", WALLET_PASSWORD).await.unwrap(), source_address);
+        // export and import only borrow the decrypted keys
+        crate::security::memory_protection::assert_no_secret_leaks();
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_existing_name_and_weak_password() {
+        let wm = manager().await;
+        wm.create_wallet("taken", WALLET_PASSWORD, false).await.unwrap();
+        let ks = wm.export_keystore_v3("taken", WALLET_PASSWORD, "Exp0rtSecret9").await.unwrap();
+        let json = ks.to_json().unwrap();
+
+        let dup = wm.import_keystore_v3("taken", &json, "Exp0rtSecret9", WALLET_PASSWORD).await;
+        assert!(matches!(dup, Err(WalletError::ValidationError(_))));
+
+        let weak = wm.import_keystore_v3("fresh", &json, "Exp0rtSecret9", "weak").await;
+        assert!(weak.is_err());
+        assert!(wm.get_wallet_by_name("fresh").await.unwrap().is_none());
+    }
+}
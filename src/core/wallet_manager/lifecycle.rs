@@ -2,6 +2,7 @@
 //!
 //! Provides wallet creation, deletion, and listing functionality
 
+use super::master_key_derivation::master_key_from_mnemonic;
 use super::WalletManager;
 use crate::core::{
     errors::WalletError,
@@ -12,6 +13,8 @@ use tracing::info;
 impl WalletManager {
     /// Create a new wallet with BIP39 mnemonic and BIP32/BIP44 key derivation
     ///
+    /// The seed uses no BIP39 passphrase; see [`Self::create_wallet_with_passphrase`].
+    ///
     /// # Arguments
     /// * `name` - Wallet name (must be unique)
     /// * `password` - User password for encrypting the master key
@@ -29,6 +32,21 @@ impl WalletManager {
         name: &str,
         password: &str,  // Password for PBKDF2 master key encryption
         quantum_safe: bool,
+    ) -> Result<(), WalletError> {
+        self.create_wallet_with_passphrase(name, password, quantum_safe, None).await
+    }
+
+    /// Create a new wallet whose seed is derived with a BIP39 passphrase
+    ///
+    /// The passphrase is not stored anywhere: recovering the wallet needs the
+    /// mnemonic *and* the passphrase, and a wrong passphrase silently yields a
+    /// different wallet. `None` or an empty passphrase is the plain BIP39 seed.
+    pub async fn create_wallet_with_passphrase(
+        &self,
+        name: &str,
+        password: &str,
+        quantum_safe: bool,
+        passphrase: Option<&str>,
     ) -> Result<(), WalletError> {
         info!("Creating wallet: {} (quantum_safe: {})", name, quantum_safe);
 
@@ -43,16 +61,36 @@ impl WalletManager {
             }
         }
 
-        // Wallet creation implementation:
-        // 1. Generate BIP39 mnemonic phrase
-        // 2. Derive master key using BIP32 standard
-        // 3. Encrypt master key with AES-256-GCM (key derived from password via PBKDF2)
-        // 4. Store encrypted wallet data in memory
+        // 1-4: mnemonic → master key → PBKDF2 + AES-256-GCM encryption
+        // The plaintext master key is zeroized when `_master_key` is dropped
+        let (wallet_data, _master_key) = self.new_wallet_data(name, password, quantum_safe, passphrase)?;
         
+        // Step 5: Store wallet data in memory
+        {
+            let mut wallets = self.wallets.write();
+            wallets.insert(name.to_string(), wallet_data);
+        }
+        
+        info!("✅ Wallet '{}' created successfully with fully encrypted master_key", name);
+        Ok(())
+    }
+
+    /// Generates a fresh HD wallet: BIP39 mnemonic, master key, and the master
+    /// key encrypted with AES-256-GCM under a PBKDF2-derived key.
+    ///
+    /// Nothing is stored; the caller decides where the wallet goes. The
+    /// plaintext master key is returned for address derivation and is
+    /// zeroized on drop. `passphrase` is the optional BIP39 passphrase mixed
+    /// into the seed.
+    pub(super) fn new_wallet_data(
+        &self,
+        name: &str,
+        password: &str,
+        quantum_safe: bool,
+        passphrase: Option<&str>,
+    ) -> Result<(SecureWalletData, zeroize::Zeroizing<[u8; 32]>), WalletError> {
         use bip39::{Language, Mnemonic};
         use rand_core::RngCore;
-        use chrono::Utc;
-        use uuid::Uuid;
         
         // Step 1: Generate 32 bytes of random entropy and create mnemonic
         let mut entropy = [0u8; 32];
@@ -63,10 +101,8 @@ impl WalletManager {
         
         info!("✅ Generated mnemonic for wallet '{}'", name);
         
-        // Step 2: Derive master key from mnemonic using BIP32 standard
-        let seed_bytes = mnemonic.to_seed("");  // Empty passphrase
-        let mut master_key = [0u8; 32];
-        master_key.copy_from_slice(&seed_bytes[..32]);
+        // Step 2: Derive master key from mnemonic (+ optional BIP39 passphrase)
+        let master_key = master_key_from_mnemonic(&mnemonic, passphrase);
         
         // Step 3: Encrypt master key using PBKDF2-derived key (consistent with decrypt_master_key)
         use aes_gcm::{Aes256Gcm, KeyInit, aead::Aead};
@@ -100,7 +136,7 @@ impl WalletManager {
         let nonce = aes_gcm::Nonce::from(nonce_bytes);  // Use `from` instead of deprecated `from_slice`
         
         // Encrypt master key with AES-256-GCM
-        let encrypted_master_key = cipher.encrypt(&nonce, master_key.as_ref())
+        let encrypted_master_key = cipher.encrypt(&nonce, &master_key[..])
             .map_err(|_| WalletError::CryptoError("Failed to encrypt master key".into()))?;
         
         info!("✅ Encrypted master_key for wallet '{}' (length: {} bytes)", 
@@ -108,12 +144,14 @@ impl WalletManager {
         
         // Step 4: Create WalletInfo and SecureWalletData structures
         let wallet_info = crate::core::wallet_info::WalletInfo {
-            id: Uuid::new_v4(),
+            id: self.ids.new_uuid(),
             name: name.to_string(),
-            created_at: Utc::now(),
+            created_at: self.clock.now(),
             quantum_safe,
             multi_sig_threshold: 1,
             networks: vec!["eth".to_string(), "btc".to_string()],
+            allowed_networks: None,
+            derivation_path: None,
         };
         
         let wallet_data = crate::core::wallet_info::SecureWalletData {
@@ -124,20 +162,11 @@ impl WalletManager {
             nonce: nonce_bytes.to_vec(),  // Save nonce for AES-GCM decryption
             schema_version: 2,
             kek_id: None,
+            key_kind: crate::core::wallet_info::WalletKeyKind::Hd,
+            passphrase_protected: passphrase.is_some_and(|p| !p.is_empty()),
         };
-        
-        // Step 5: Store wallet data in memory
-        {
-            let mut wallets = self.wallets.write();
-            wallets.insert(name.to_string(), wallet_data);
-        }
-        
-        // Cleanup sensitive data from memory
-        use zeroize::Zeroize;
-        master_key.zeroize();
-        
-        info!("✅ Wallet '{}' created successfully with fully encrypted master_key", name);
-        Ok(())
+
+        Ok((wallet_data, master_key))
     }
 
     /// Delete a wallet by name
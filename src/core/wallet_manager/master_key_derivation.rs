@@ -25,8 +25,11 @@
 //! - 使用zeroizeClear敏感数据
 //! - 常量时间比较防止侧信道攻击
 
+use super::derivation::DerivationPath;
 use super::WalletManager;
 use crate::core::errors::WalletError;
+use crate::core::wallet_info::{SecureWalletData, WalletKeyKind};
+use crate::security::memory_protection::TaintedBytes;
 use tracing::{debug, info};
 
 impl WalletManager {
@@ -56,8 +59,8 @@ impl WalletManager {
                 format!("Wallet '{}' not found", wallet_name)
             ))?;
         
-        // 2. 解密master_key (使用transactions.rs中已有的方法)
-        let master_key = self.decrypt_master_key(&wallet, password).await?;
+        // 2. 解密signing key（wallet设置了派生路径时为路径下的子密钥）
+        let master_key = self.decrypt_signing_key(&wallet, password).await?;
         
         // 3. 使用BIP32frommaster_key推导以太坊address
         let address = derive_ethereum_address_from_key(&master_key)?;
@@ -80,8 +83,8 @@ impl WalletManager {
                 format!("Wallet '{}' not found", wallet_name)
             ))?;
         
-        // 2. 解密master_key (使用transactions.rs中已有的方法)
-        let master_key = self.decrypt_master_key(&wallet, password).await?;
+        // 2. 解密signing key（wallet设置了派生路径时为路径下的子密钥）
+        let master_key = self.decrypt_signing_key(&wallet, password).await?;
         
         // 3. 使用BIP32frommaster_key推导比特币address
         let address = derive_bitcoin_address_from_key(&master_key)?;
@@ -90,8 +93,68 @@ impl WalletManager {
         Ok(address)
     }
     
-    // ✅ decrypt_master_key已在transactions.rs中实现
-    // 这里无需重复定义
+    /// 按指定或wallet默认的派生路径推导address
+    ///
+    /// `path` 为 `None` 时使用wallet创建时保存的 `derivation_path`，
+    /// 两者都没有时与 [`derive_address`](Self::derive_address) 相同，直接使用主密钥。
+    ///
+    /// # Errors
+    /// * `WalletError::NotFoundError` - wallet不存在
+    /// * `WalletError::UnsupportedWalletKind` - 为导入的单个Private key指定了路径
+    /// * `WalletError::CryptoError` - Password错误
+    pub async fn derive_wallet_address(
+        &self,
+        wallet_name: &str,
+        password: &str,
+        network: &str,
+        path: Option<&DerivationPath>,
+    ) -> Result<String, WalletError> {
+        let wallet = self.get_wallet_by_name(wallet_name).await?
+            .ok_or_else(|| WalletError::NotFoundError(
+                format!("Wallet '{}' not found", wallet_name)
+            ))?;
+        if path.is_some() && wallet.key_kind != WalletKeyKind::Hd {
+            return Err(WalletError::UnsupportedWalletKind(format!(
+                "Wallet '{}' holds a single imported key and cannot derive child keys",
+                wallet_name
+            )));
+        }
+
+        let path = path.or(wallet.info.derivation_path.as_ref());
+        let master_key = self.decrypt_master_key(&wallet, password).await?;
+        self.derive_address(&master_key, network, path)
+    }
+
+    /// 解密wallet的signing key
+    ///
+    /// wallet保存了 `derivation_path` 时以主密钥为 BIP32 种子派生该路径下的子密钥，
+    /// 否则返回主密钥本身。sign与address推导都经由此处，保证与创建时的address一致。
+    /// 导出/备份主密钥的路径仍使用 `decrypt_master_key`。
+    pub(super) async fn decrypt_signing_key(
+        &self,
+        wallet: &SecureWalletData,
+        password: &str,
+    ) -> Result<TaintedBytes, WalletError> {
+        let master_key = self.decrypt_master_key(wallet, password).await?;
+        match &wallet.info.derivation_path {
+            Some(path) => Ok(TaintedBytes::new(path.derive_key(&master_key)?, "derived signing key")),
+            None => Ok(master_key),
+        }
+    }
+}
+
+/// frommnemonic推导master_key（BIP39 seed 的前32字节）
+///
+/// `passphrase` 即 BIP39 的"第25个词"，参与 seed 的 PBKDF2 派生。任何 passphrase
+/// 都能得出合法的 seed，输错不会报错，只会得到另一组address；`None` 与空串等价。
+pub(super) fn master_key_from_mnemonic(
+    mnemonic: &bip39::Mnemonic,
+    passphrase: Option<&str>,
+) -> zeroize::Zeroizing<[u8; 32]> {
+    let seed = zeroize::Zeroizing::new(mnemonic.to_seed(passphrase.unwrap_or("")));
+    let mut master_key = zeroize::Zeroizing::new([0u8; 32]);
+    master_key.copy_from_slice(&seed[..32]);
+    master_key
 }
 
 /// frommaster_key推导Ethereumaddress
@@ -107,7 +170,7 @@ impl WalletManager {
 ///
 /// # Returns
 /// * Ethereumaddress (checksummed)
-fn derive_ethereum_address_from_key(master_key: &[u8]) -> Result<String, WalletError> {
+pub(super) fn derive_ethereum_address_from_key(master_key: &[u8]) -> Result<String, WalletError> {
     debug!("Deriving Ethereum address from master_key");
     
     #[cfg(feature = "ethereum")]
@@ -238,6 +301,37 @@ mod tests {
         println!("Derived Bitcoin address: {}", address);
     }
     
+    /// BIP39 官方测试向量（passphrase "TREZOR"）与空 passphrase 的 seed 前32字节
+    #[test]
+    fn test_master_key_from_mnemonic_bip39_vectors() {
+        let vectors = [
+            (
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+                "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc1",
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e5349553",
+            ),
+            (
+                "legal winner thank year wave sausage worth useful legal winner thank yellow",
+                "878386efb78845b3355bd15ea4d39ef97d179cb712b77d5c12b6be415fffeffe",
+                "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6f",
+            ),
+        ];
+        for (phrase, plain, trezor) in vectors {
+            let mnemonic = bip39::Mnemonic::parse(phrase).unwrap();
+            assert_eq!(hex::encode(*master_key_from_mnemonic(&mnemonic, None)), plain);
+            assert_eq!(hex::encode(*master_key_from_mnemonic(&mnemonic, Some(""))), plain);
+            let protected = master_key_from_mnemonic(&mnemonic, Some("TREZOR"));
+            assert_eq!(hex::encode(*protected), trezor);
+
+            // passphrase 不同，派生address也不同
+            let plain_key = master_key_from_mnemonic(&mnemonic, None);
+            assert_ne!(
+                derive_ethereum_address_from_key(&plain_key[..]).unwrap(),
+                derive_ethereum_address_from_key(&protected[..]).unwrap()
+            );
+        }
+    }
+
     #[test]
     fn test_invalid_key_length() {
         let invalid_key = [0u8; 16]; // 只有16字节
@@ -5,17 +5,23 @@
 //! ## Module Structure
 //! - `lifecycle` - Wallet lifecycle management (create, delete, list)
 //! - `keys` - Key management (generation, derivation, rotation)
+//! - `keystore` - Ethereum keystore V3 import/export
 //! - `transactions` - Transaction operations (send, multi-sig)
 //! - `balance` - Balance queries
 //! - `backup` - Backup and recovery
 //! - `bridge` - Cross-chain bridging
 //! - `nonce` - Nonce management
 //! - `address` - Address derivation
+//! - `network_init` - Atomic multi-network wallet creation
+//! - `network_policy` - Per-wallet network allowlist
+//! - `reconcile` - Stored transactions against on-chain receipts
+//! - `btc_descriptor` - Bitcoin account xpub / output descriptor export
 //! - `testing` - Testing utilities
 
 // Submodule declarations
 pub mod lifecycle;      // Wallet lifecycle (create, delete, list)
 pub mod keys;           // Key management (generation, derivation, rotation)
+pub mod keystore;       // Keystore V3 import/export
 pub mod transactions;   // Transaction operations (send, multi-sig)
 pub mod balance;        // Balance queries
 pub mod backup;         // Backup and recovery
@@ -28,6 +34,13 @@ pub mod signing;        // Transaction signing (Ethereum)
 pub mod bitcoin_utxo;   // Bitcoin UTXO management
 pub mod bitcoin_signing; // Bitcoin transaction signing
 pub mod tx_history;     // Transaction history queries
+pub mod network_init;   // Multi-network wallet initialization
+pub mod network_policy; // Per-wallet network allowlist
+pub mod reconcile;      // On-chain reconciliation of stored transactions
+#[cfg(feature = "bitcoin")]
+pub mod btc_descriptor; // Bitcoin xpub / descriptor export (watch-only)
+
+pub use network_init::{probe_network, CreateWalletOptions, NetworkInitStatus};
 
 // Testing utilities module
 #[cfg(any(test, feature = "test-env"))]
@@ -53,9 +66,11 @@ use crate::blockchain::{
     bridge::BridgeTransaction,
 };
 use crate::core::{
+    clock::{system_clock, Clock},
     config::WalletConfig,
     errors::WalletError,
-    wallet_info::SecureWalletData,
+    ids::{random_ids, IdGenerator},
+    wallet_info::{SecureWalletData, WalletInfo},
 };
 
 // Ethereum temporarily disabled (dependency conflicts)
@@ -79,6 +94,12 @@ pub struct WalletManager {
     
     /// 桥接transaction存储
     pub bridge_transactions: Arc<RwLock<HashMap<String, BridgeTransaction>>>,
+
+    /// 创建时间来源（测试中固定）
+    pub clock: Arc<dyn Clock>,
+
+    /// wallet与桥接记录的 id 来源（测试中按序递增）
+    pub ids: Arc<dyn IdGenerator>,
     
     // Ethereum 客户端缓存 (暂时禁用)
     // #[cfg(feature = "ethereum")]
@@ -112,12 +133,31 @@ impl WalletManager {
             wallets: Arc::new(RwLock::new(HashMap::new())),
             nonce_tracker: Arc::new(RwLock::new(HashMap::new())),
             bridge_transactions: Arc::new(RwLock::new(HashMap::new())),
+            clock: system_clock(),
+            ids: random_ids(),
             // Ethereum 暂时禁用
             // #[cfg(feature = "ethereum")]
             // ethereum_clients: Arc::new(RwLock::new(HashMap::new())),
             // #[cfg(feature = "polygon")]
         })
     }
+
+    /// 替换时钟（快照测试注入固定时间）
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 替换 id 生成器
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// [`WalletInfo::new`]，id 与创建时间取自注入的生成器与时钟
+    pub(crate) fn new_wallet_info(&self, name: &str, quantum_safe: bool) -> WalletInfo {
+        WalletInfo { id: self.ids.new_uuid(), created_at: self.clock.now(), ..WalletInfo::new(name, quantum_safe) }
+    }
 }
 
 
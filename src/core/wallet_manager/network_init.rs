@@ -0,0 +1,190 @@
+//! Multi-network wallet initialization
+//!
+//! `create_wallet_full` creates the key material and registers the wallet on
+//! every requested network (address, seeded nonce, deposit-scan cursor) in a
+//! single storage transaction. RPC failures do not abort creation: the network
+//! is written as `needs_sync` and can be completed later with
+//! `initialize_network`.
+
+use serde::Serialize;
+use tracing::{info, warn};
+
+use super::derivation::DerivationPath;
+use super::WalletManager;
+use crate::blockchain::client_registry::ClientRegistry;
+use crate::core::errors::WalletError;
+use crate::storage::{NetworkInit, WalletStorage};
+
+/// Options for [`WalletManager::create_wallet_full`]
+#[derive(Debug, Clone, Default)]
+pub struct CreateWalletOptions {
+    /// Password for PBKDF2 master key encryption
+    pub password: String,
+    pub quantum_safe: bool,
+    /// Networks to register the wallet on (duplicates are ignored)
+    pub networks: Vec<String>,
+    /// BIP32 path of the signing key, remembered as the wallet's default;
+    /// `None` signs with the master key itself
+    pub derivation_path: Option<DerivationPath>,
+    /// BIP39 passphrase mixed into the seed; never stored
+    pub passphrase: Option<String>,
+}
+
+/// Per-network outcome returned to the caller
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NetworkInitStatus {
+    pub network: String,
+    pub address: String,
+    /// `ready` | `needs_sync`
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<&NetworkInit> for NetworkInitStatus {
+    fn from(init: &NetworkInit) -> Self {
+        Self {
+            network: init.network.clone(),
+            address: init.address.clone(),
+            status: init.status().to_string(),
+            nonce: init.nonce,
+            error: init.error.clone(),
+        }
+    }
+}
+
+/// Fetches the on-chain nonce and tip height for `address`.
+///
+/// Never fails: whatever could not be fetched is left `None` and the error is
+/// kept so the network is recorded as `needs_sync`.
+pub async fn probe_network(clients: &ClientRegistry, network: &str, address: &str) -> NetworkInit {
+    let mut init = NetworkInit {
+        network: network.to_string(),
+        address: address.to_string(),
+        nonce: None,
+        scan_from_block: None,
+        error: None,
+    };
+    let client = match clients.get(network) {
+        Ok(client) => client,
+        Err(e) => {
+            init.error = Some(e.to_string());
+            return init;
+        }
+    };
+    match client.get_nonce(address).await {
+        Ok(nonce) => init.nonce = Some(nonce),
+        Err(e) => init.error = Some(e.to_string()),
+    }
+    match client.get_block_number().await {
+        Ok(height) => init.scan_from_block = Some(height),
+        Err(e) => {
+            init.error.get_or_insert_with(|| e.to_string());
+        }
+    }
+    if let Some(error) = &init.error {
+        warn!("network {} initialization for {} degraded to needs_sync: {}", network, address, error);
+    }
+    init
+}
+
+impl WalletManager {
+    /// Create a wallet and register it on `options.networks` in one step
+    ///
+    /// Addresses are derived and nonces/tip heights fetched before anything is
+    /// written; the wallet row and every network row are then committed in a
+    /// single storage transaction, and the wallet is only added to memory
+    /// after that commit. A failure at any point leaves no trace of the wallet.
+    ///
+    /// # Errors
+    /// * `WalletError::ValidationError` - Duplicate name or a network addresses can't be derived for
+    /// * `WalletError::StorageError` - The storage transaction failed (nothing was written)
+    pub async fn create_wallet_full(
+        &self,
+        name: &str,
+        options: CreateWalletOptions,
+        storage: &WalletStorage,
+        clients: &ClientRegistry,
+    ) -> Result<Vec<NetworkInitStatus>, WalletError> {
+        info!("Creating wallet {} on networks {:?}", name, options.networks);
+
+        let in_memory = self.wallets.read().contains_key(name);
+        if in_memory || storage.load_wallet(name).await.is_ok() {
+            return Err(WalletError::ValidationError(format!("Wallet '{}' already exists", name)));
+        }
+
+        let mut networks: Vec<String> = Vec::new();
+        for network in options.networks {
+            if !networks.contains(&network) {
+                networks.push(network);
+            }
+        }
+
+        let (mut wallet_data, master_key) =
+            self.new_wallet_data(name, &options.password, options.quantum_safe, options.passphrase.as_deref())?;
+        let path = options.derivation_path.as_ref();
+        let addresses = networks
+            .iter()
+            .map(|network| Ok((network.clone(), self.derive_address(&master_key[..], network, path)?)))
+            .collect::<Result<Vec<_>, WalletError>>()?;
+        drop(master_key);
+
+        let inits = futures::future::join_all(
+            addresses.iter().map(|(network, address)| probe_network(clients, network, address)),
+        )
+        .await;
+
+        wallet_data.info.networks = networks;
+        wallet_data.info.derivation_path = options.derivation_path;
+        let serialized = bincode::serialize(&wallet_data)
+            .map_err(|e| WalletError::SerializationError(e.to_string()))?;
+        storage
+            .create_wallet_with_networks(name, &serialized, options.quantum_safe, &inits)
+            .await
+            .map_err(|e| WalletError::StorageError(e.to_string()))?;
+
+        self.wallets.write().insert(name.to_string(), wallet_data);
+        info!("✅ Wallet '{}' created on {} network(s)", name, inits.len());
+        Ok(inits.iter().map(NetworkInitStatus::from).collect())
+    }
+
+    /// Register an existing wallet on one more network, or finish a
+    /// `needs_sync` registration once the RPC is reachable again
+    ///
+    /// The password is needed to derive the network address from the master key,
+    /// along the wallet's stored derivation path if it has one.
+    pub async fn initialize_network(
+        &self,
+        name: &str,
+        network: &str,
+        password: &str,
+        storage: &WalletStorage,
+        clients: &ClientRegistry,
+    ) -> Result<NetworkInitStatus, WalletError> {
+        let wallet_data = self
+            .wallets
+            .read()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| WalletError::NotFoundError(format!("Wallet '{}' not found", name)))?;
+        self.ensure_network_allowed(name, network)?;
+        let master_key = self.decrypt_master_key(&wallet_data, password).await?;
+        let address = self.derive_address(&master_key, network, wallet_data.info.derivation_path.as_ref())?;
+        drop(master_key);
+
+        let init = probe_network(clients, network, &address).await;
+        storage
+            .initialize_wallet_networks(name, std::slice::from_ref(&init))
+            .await
+            .map_err(|e| WalletError::StorageError(e.to_string()))?;
+
+        if let Some(wallet) = self.wallets.write().get_mut(name) {
+            if !wallet.info.networks.iter().any(|n| n == network) {
+                wallet.info.networks.push(network.to_string());
+            }
+        }
+        Ok(NetworkInitStatus::from(&init))
+    }
+}
@@ -0,0 +1,107 @@
+//! Per-wallet network allowlist
+//!
+//! `WalletInfo::allowed_networks` 为 `None` 时wallet可用于任何network。
+//! 设置后，balance、发送、多签、桥接（两端）和address派生在动手前都经过
+//! [`WalletManager::ensure_network_allowed`]，不在集合内的network返回
+//! `WalletError::NetworkNotAllowed`。
+
+use tracing::{debug, info};
+
+use super::WalletManager;
+use crate::core::errors::WalletError;
+use crate::storage::WalletStorage;
+
+impl WalletManager {
+    /// 当前的允许集合；wallet不存在时为 `NotFoundError`
+    pub fn allowed_networks(&self, wallet_name: &str) -> Result<Option<Vec<String>>, WalletError> {
+        self.wallets
+            .read()
+            .get(wallet_name)
+            .map(|w| w.info.allowed_networks.clone())
+            .ok_or_else(|| WalletError::NotFoundError(format!("Wallet '{}' not found", wallet_name)))
+    }
+
+    /// 拒绝wallet允许集合之外的network；放行时返回本次check所用的集合（`None` 为不受限）
+    ///
+    /// 不由本管理器托管的wallet（非托管绑定）没有集合，直接放行；
+    /// wallet是否存在由各操作自己check。
+    pub fn ensure_network_allowed(
+        &self,
+        wallet_name: &str,
+        network: &str,
+    ) -> Result<Option<Vec<String>>, WalletError> {
+        let wallets = self.wallets.read();
+        let Some(wallet) = wallets.get(wallet_name) else {
+            return Ok(None);
+        };
+        if wallet.info.allows_network(network) {
+            return Ok(wallet.info.allowed_networks.clone());
+        }
+        let allowed = wallet.info.allowed_networks.as_deref().unwrap_or_default();
+        Err(WalletError::NetworkNotAllowed(format!(
+            "wallet '{}' may not use {} (allowed: [{}])",
+            wallet_name,
+            network,
+            allowed.join(", ")
+        )))
+    }
+
+    /// 替换wallet的允许集合（`None` 取消限制），返回之前的集合
+    ///
+    /// 已由 `create_wallet_full` 持久化的wallet同时改写存储中的记录；
+    /// 存储写入失败时内存中的集合保持不变。
+    pub async fn set_allowed_networks(
+        &self,
+        wallet_name: &str,
+        allowed: Option<Vec<String>>,
+        storage: &WalletStorage,
+    ) -> Result<Option<Vec<String>>, WalletError> {
+        let mut wallet_data = self
+            .wallets
+            .read()
+            .get(wallet_name)
+            .cloned()
+            .ok_or_else(|| WalletError::NotFoundError(format!("Wallet '{}' not found", wallet_name)))?;
+        let previous = std::mem::replace(&mut wallet_data.info.allowed_networks, allowed.clone());
+
+        if storage.load_wallet(wallet_name).await.is_ok() {
+            let serialized = bincode::serialize(&wallet_data)
+                .map_err(|e| WalletError::SerializationError(e.to_string()))?;
+            storage
+                .update_wallet_encrypted_data(wallet_name, &serialized)
+                .await
+                .map_err(|e| WalletError::StorageError(e.to_string()))?;
+        } else {
+            debug!("wallet {} is not persisted; allowlist kept in memory", wallet_name);
+        }
+
+        if let Some(wallet) = self.wallets.write().get_mut(wallet_name) {
+            wallet.info.allowed_networks = allowed;
+        }
+        info!("allowed networks of wallet {} updated", wallet_name);
+        Ok(previous)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::WalletConfig;
+
+    #[tokio::test]
+    async fn test_unrestricted_and_unmanaged_wallets_pass() {
+        std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+        let manager = WalletManager::new(&WalletConfig::default()).await.unwrap();
+        manager.create_wallet("open", "test_password", false).await.unwrap();
+
+        assert_eq!(manager.allowed_networks("open").unwrap(), None);
+        assert!(manager.ensure_network_allowed("open", "btc").is_ok());
+        assert!(manager.ensure_network_allowed("not-managed", "btc").is_ok());
+        assert!(matches!(manager.allowed_networks("not-managed"), Err(WalletError::NotFoundError(_))));
+
+        manager.wallets.write().get_mut("open").unwrap().info.allowed_networks = Some(vec!["eth".to_string()]);
+        assert_eq!(manager.ensure_network_allowed("open", "eth").unwrap(), Some(vec!["eth".to_string()]));
+        let err = manager.ensure_network_allowed("open", "polygon").unwrap_err();
+        assert!(matches!(err, WalletError::NetworkNotAllowed(ref msg) if msg.contains("[eth]")), "{}", err);
+    }
+}
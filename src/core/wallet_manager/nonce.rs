@@ -1,10 +1,30 @@
 //! Nonce 管理模块
 //!
-//! 提供transaction nonce 的追踪和管理功能
+//! 提供transaction nonce 的追踪和管理功能，以及存储的 nonce 计数器与链上transaction数的对账
 
 use super::WalletManager;
 use crate::core::errors::WalletError;
-use tracing::{debug, info};
+use crate::intents::BroadcastChain;
+use crate::storage::WalletStorage;
+use ethers::types::Address;
+use serde::Serialize;
+use tracing::{debug, info, warn};
+
+/// [`WalletManager::reconcile_nonces`] 的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NonceReconciliation {
+    pub network: String,
+    /// 小写十六进制
+    pub address: String,
+    /// 链上transaction数（含 pending）
+    pub on_chain: u64,
+    /// 对账前存储的下一个 nonce
+    pub stored_next: u64,
+    /// 归还给计数器的 nonce，从高到低
+    pub released: Vec<u64>,
+    /// 链上没有、也未能归还的 nonce：预留不足 `min_age`、或没有预留记录
+    pub gaps: Vec<u64>,
+}
 
 impl WalletManager {
     /// fetch下一个 nonce
@@ -98,6 +118,57 @@ impl WalletManager {
 
         Ok(())
     }
+
+    /// 对账存储的 nonce 计数器与链上transaction数
+    ///
+    /// 链上已计入的预留标记为 confirmed；计数器高于链上时，从最高的 nonce 往下
+    /// 归还已预留超过 `min_age` 仍未上链的（被 mempool 丢弃的transaction），遇到
+    /// 不能归还的即停止，其余作为 gap 报告。`min_age` 避免收回刚预留、尚在广播途中的 nonce。
+    ///
+    /// # Errors
+    /// Password错误、链上query或存储failed
+    pub async fn reconcile_nonces(
+        &self,
+        wallet_name: &str,
+        password: &str,
+        network: &str,
+        storage: &WalletStorage,
+        chain: &dyn BroadcastChain,
+        min_age: chrono::Duration,
+    ) -> Result<NonceReconciliation, WalletError> {
+        let address = self.derive_wallet_address(wallet_name, password, network, None).await?;
+        let address: Address =
+            address.parse().map_err(|e| WalletError::AddressError(format!("Invalid Ethereum address: {}", e)))?;
+        let key = format!("{:#x}", address);
+        let storage_error = |e: anyhow::Error| WalletError::StorageError(e.to_string());
+
+        let on_chain = chain.transaction_count(network, address).await?;
+        storage.confirm_nonces_below(network, &key, on_chain).await.map_err(storage_error)?;
+        let stored_next = storage.next_nonce(network, &key).await.map_err(storage_error)?.unwrap_or(0);
+        let unconfirmed = storage.list_reserved_unconfirmed_nonces(network, &key).await.map_err(storage_error)?;
+        let cutoff = (storage.clock().now() - min_age).timestamp();
+
+        let mut released = Vec::new();
+        let mut next = stored_next;
+        while next > on_chain {
+            let nonce = next - 1;
+            let stale = unconfirmed.iter().any(|r| r.nonce as u64 == nonce && r.reserved_at <= cutoff);
+            if !stale || !storage.release_nonce(network, &key, nonce).await.map_err(storage_error)? {
+                break;
+            }
+            released.push(nonce);
+            next = nonce;
+        }
+        let gaps: Vec<u64> = (on_chain..next).collect();
+        if !gaps.is_empty() {
+            warn!("nonces {:?} of {} on {} are not on chain and stay reserved", gaps, key, network);
+        }
+        info!(
+            "reconciled nonces of {} on {}: on chain {}, stored {}, released {:?}",
+            wallet_name, network, on_chain, stored_next, released
+        );
+        Ok(NonceReconciliation { network: network.to_string(), address: key, on_chain, stored_next, released, gaps })
+    }
 }
 
 #[cfg(test)]
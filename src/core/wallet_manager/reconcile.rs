@@ -0,0 +1,515 @@
+//! On-chain reconciliation of stored transactions
+//!
+//! [`WalletManager::reconcile_onchain`] audits a time range after an incident
+//! (RPC outage, crashed instance) in two passes per network:
+//!
+//! - every stored transaction created in the range is compared with its
+//!   receipt: outcome, block (against the backfilled ledger), the direction
+//!   the wallet's address appears in, and for sends the fee paid;
+//! - every transfer of a registered address mined in the range is read with
+//!   the backfill machinery ([`HistorySource`]) and looked up in storage.
+//!
+//! Findings are reported as [`Discrepancy`]s. With [`ReconcileMode::Apply`]
+//! the safe ones are corrected through the normal recording paths: a pending
+//! or expired record takes the receipt's outcome, a missing fee is filled in
+//! and a missing record is created like a backfill batch. Anything that would
+//! overwrite or delete what was recorded (a confirmed record the chain does
+//! not know, a fee or outcome that differs) stays report-only.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use ethers::types::{Address, TransactionReceipt, H256, U256, U64};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use super::WalletManager;
+use crate::blockchain::history::{HistoryRpc, HistorySource, RawTransfer};
+use crate::core::errors::WalletError;
+use crate::ops::backfill::normalize;
+use crate::storage::{
+    Discrepancy, DiscrepancyKind, LedgerScope, ReconciliationTotals, TransactionFilter, TransactionRecord,
+    WalletStorage,
+};
+
+/// Stored transactions read per query
+const RECORD_PAGE_SIZE: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconcileMode {
+    /// Only report
+    Report,
+    /// Also correct what is safe to correct
+    Apply,
+}
+
+/// Unix seconds, `from..to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconcileRange {
+    pub from: i64,
+    pub to: i64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReconcileError {
+    #[error("unknown network {0}")]
+    UnknownNetwork(String),
+    #[error("empty range {0}..{1}")]
+    InvalidRange(i64, i64),
+    #[error(transparent)]
+    Chain(#[from] WalletError),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+    #[error("reconciliation cancelled")]
+    Cancelled,
+}
+
+/// Where a run is, for progress reporting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReconcileProgress {
+    pub network: String,
+    /// `records` (stored transactions) or `chain` (transfers of our addresses)
+    pub stage: &'static str,
+    pub done: u64,
+    pub total: u64,
+}
+
+/// What a run reads and who asked for it
+pub struct ReconcileContext<'a> {
+    pub storage: &'a WalletStorage,
+    /// Receipts and block timestamps
+    pub rpc: &'a dyn HistoryRpc,
+    /// The history source of a network, as the backfill would use it
+    pub history: &'a (dyn Fn(&str) -> Arc<dyn HistorySource> + Send + Sync),
+    pub progress: Option<&'a (dyn Fn(&ReconcileProgress) + Send + Sync)>,
+    pub cancel: &'a CancellationToken,
+    /// Recorded with every correction in the audit log
+    pub requested_by: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReconciliationReport {
+    pub range: ReconcileRange,
+    pub networks: Vec<String>,
+    pub apply: bool,
+    #[serde(flatten)]
+    pub totals: ReconciliationTotals,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl WalletManager {
+    /// Audits `range` on `networks` (every configured network when empty)
+    /// against the chain; see the module docs.
+    pub async fn reconcile_onchain(
+        &self,
+        ctx: &ReconcileContext<'_>,
+        range: ReconcileRange,
+        networks: &[String],
+        mode: ReconcileMode,
+    ) -> Result<ReconciliationReport, ReconcileError> {
+        if range.from >= range.to {
+            return Err(ReconcileError::InvalidRange(range.from, range.to));
+        }
+        let mut networks = if networks.is_empty() {
+            self.config.blockchain.networks.keys().cloned().collect()
+        } else {
+            networks.to_vec()
+        };
+        networks.sort();
+        networks.dedup();
+        if let Some(unknown) = networks.iter().find(|n| !self.config.blockchain.networks.contains_key(*n)) {
+            return Err(ReconcileError::UnknownNetwork(unknown.clone()));
+        }
+
+        let mut run = Reconciliation {
+            ctx,
+            range,
+            apply: mode == ReconcileMode::Apply,
+            blocks_per_batch: self.config.backfill.blocks_per_batch.max(1),
+            manager: self,
+            totals: ReconciliationTotals::default(),
+            discrepancies: Vec::new(),
+        };
+        for network in &networks {
+            run.stored_records(network).await?;
+            run.chain_transfers(network).await?;
+        }
+        run.totals.discrepancies = run.discrepancies.len() as u64;
+        run.totals.applied = run.discrepancies.iter().filter(|d| d.applied).count() as u64;
+        info!(
+            "reconciled {}..{} on {}: {} records checked, {} transfers scanned, {} discrepancies ({} corrected)",
+            range.from,
+            range.to,
+            networks.join(","),
+            run.totals.checked,
+            run.totals.scanned,
+            run.totals.discrepancies,
+            run.totals.applied
+        );
+        Ok(ReconciliationReport {
+            range,
+            networks,
+            apply: run.apply,
+            totals: run.totals,
+            discrepancies: run.discrepancies,
+        })
+    }
+}
+
+/// The addresses on either side of a transaction, from the wallet's view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Sides {
+    sent: bool,
+    received: bool,
+}
+
+impl Sides {
+    fn describe(self) -> &'static str {
+        match (self.sent, self.received) {
+            (true, true) => "self",
+            (true, false) => "out",
+            (false, true) => "in",
+            (false, false) => "unrelated",
+        }
+    }
+}
+
+struct Reconciliation<'a, 'c> {
+    ctx: &'a ReconcileContext<'c>,
+    manager: &'a WalletManager,
+    range: ReconcileRange,
+    apply: bool,
+    blocks_per_batch: u64,
+    totals: ReconciliationTotals,
+    discrepancies: Vec<Discrepancy>,
+}
+
+impl Reconciliation<'_, '_> {
+    fn report(&self, network: &str, stage: &'static str, done: u64, total: u64) {
+        if let Some(progress) = self.ctx.progress {
+            progress(&ReconcileProgress { network: network.to_string(), stage, done, total });
+        }
+    }
+
+    fn check_cancelled(&self) -> Result<(), ReconcileError> {
+        if self.ctx.cancel.is_cancelled() {
+            return Err(ReconcileError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Wallet name → address on `network`
+    async fn addresses(&self, network: &str) -> Result<BTreeMap<String, String>, ReconcileError> {
+        let rows = self.ctx.storage.network_wallets(network).await?;
+        Ok(rows.into_iter().map(|row| (row.wallet_name, row.address)).collect())
+    }
+
+    async fn stored_records(&mut self, network: &str) -> Result<(), ReconcileError> {
+        // records carry `wallets.id`; findings and the ledger use the name
+        let mut wallets = BTreeMap::new();
+        for (name, address) in self.addresses(network).await? {
+            if let Some(id) = self.ctx.storage.wallet_id(&name).await? {
+                wallets.insert(id, (name, address));
+            }
+        }
+        let filter = TransactionFilter {
+            networks: vec![network.to_string()],
+            created_after: chrono::DateTime::from_timestamp(self.range.from, 0),
+            created_before: chrono::DateTime::from_timestamp(self.range.to, 0),
+            ..Default::default()
+        };
+        // corrections change status and fee only, so the pages stay put
+        let mut offset = 0;
+        loop {
+            let (records, total) = self.ctx.storage.query_transactions(&filter, offset, RECORD_PAGE_SIZE).await?;
+            for record in &records {
+                self.check_cancelled()?;
+                let (name, address) = match wallets.get(&record.wallet_id) {
+                    Some((name, address)) => (name.as_str(), address.parse::<Address>().ok()),
+                    None => (record.wallet_id.as_str(), None),
+                };
+                self.check_record(record, name, address).await?;
+                offset += 1;
+                self.report(network, "records", offset as u64, total as u64);
+            }
+            if records.len() < RECORD_PAGE_SIZE {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn check_record(
+        &mut self,
+        record: &TransactionRecord,
+        wallet_name: &str,
+        address: Option<Address>,
+    ) -> Result<(), ReconcileError> {
+        self.totals.checked += 1;
+        let receipt = match record.tx_hash.parse::<H256>() {
+            Ok(hash) => self.ctx.rpc.transaction_receipt(&record.network, hash).await?,
+            Err(_) => None,
+        };
+        let Some(receipt) = receipt else {
+            match record.status.as_str() {
+                "confirmed" | "failed" => {
+                    self.push(
+                        record,
+                        wallet_name,
+                        DiscrepancyKind::OrphanRecord,
+                        Some(record.status.clone()),
+                        None,
+                        format!("recorded as {} but the chain has no receipt", record.status),
+                        false,
+                    );
+                }
+                "pending" => self.totals.unmined += 1,
+                _ => {}
+            }
+            return Ok(());
+        };
+
+        let recorded_sides = address.map(|a| Sides {
+            sent: same_address(&record.from_address, a),
+            received: same_address(&record.to_address, a),
+        });
+        let chain_sides = address.map(|a| receipt_sides(&receipt, a));
+        if let (Some(recorded), Some(chain)) = (recorded_sides, chain_sides) {
+            if recorded != chain {
+                self.push(
+                    record,
+                    wallet_name,
+                    DiscrepancyKind::UnknownDirection,
+                    Some(recorded.describe().to_string()),
+                    Some(chain.describe().to_string()),
+                    format!("recorded {} -> {}, mined from {:?}", record.from_address, record.to_address, receipt.from),
+                    false,
+                );
+            }
+        }
+
+        let onchain_status = if receipt.status == Some(U64::zero()) { "failed" } else { "confirmed" };
+        if record.status != onchain_status {
+            // pending and expired were never a final word; the others were
+            let correctable = matches!(record.status.as_str(), "pending" | "expired");
+            let index = self.push(
+                record,
+                wallet_name,
+                DiscrepancyKind::StatusMismatch,
+                Some(record.status.clone()),
+                Some(onchain_status.to_string()),
+                format!("recorded as {}, mined as {}", record.status, onchain_status),
+                correctable,
+            );
+            if correctable && self.apply {
+                let confirmed_at = (onchain_status == "confirmed").then(|| self.manager.clock.now());
+                self.ctx.storage.update_transaction_status(&record.id, onchain_status, confirmed_at).await?;
+                self.applied(index).await;
+            }
+        }
+
+        let mined_in = receipt.block_number.map(|b| b.as_u64() as i64);
+        let ledger = self.ctx.storage.ledger_entries_for_transaction(&record.network, &record.tx_hash).await?;
+        let moved = ledger.iter().find(|e| e.wallet_id == wallet_name && Some(e.block_number) != mined_in);
+        if let Some(entry) = moved {
+            self.push(
+                record,
+                wallet_name,
+                DiscrepancyKind::StatusMismatch,
+                Some(format!("block {}", entry.block_number)),
+                mined_in.map(|b| format!("block {}", b)),
+                "ledger entry is in a different block than the receipt".to_string(),
+                false,
+            );
+        }
+
+        // only the sender pays, and a receipt without a price cannot say how much
+        let sender = recorded_sides.is_some_and(|s| s.sent) && chain_sides.is_some_and(|s| s.sent);
+        if let (true, Some(gas_used), Some(price)) = (sender, receipt.gas_used, receipt.effective_gas_price) {
+            let paid = gas_used.saturating_mul(price);
+            let recorded = ethers::utils::parse_ether(record.fee.trim()).ok();
+            if recorded != Some(paid) {
+                let unset = recorded.is_some_and(|fee| fee.is_zero()) || record.fee.trim().is_empty();
+                let onchain = ethers::utils::format_ether(paid);
+                let detail = if unset { "fee was not recorded" } else { "recorded fee differs from the gas paid" };
+                let index = self.push(
+                    record,
+                    wallet_name,
+                    DiscrepancyKind::FeeMismatch,
+                    Some(record.fee.clone()),
+                    Some(onchain.clone()),
+                    detail.to_string(),
+                    unset,
+                );
+                if unset && self.apply && self.ctx.storage.fill_transaction_fee(&record.id, &onchain).await? {
+                    self.applied(index).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn chain_transfers(&mut self, network: &str) -> Result<(), ReconcileError> {
+        let addresses = self.addresses(network).await?;
+        if addresses.is_empty() {
+            return Ok(());
+        }
+        let source = (self.ctx.history)(network);
+        let head = source.head(network).await?;
+        let first = self.first_block_at(network, head, self.range.from).await?;
+        let end = self.first_block_at(network, head, self.range.to).await?;
+        if first >= end {
+            return Ok(());
+        }
+        let batches = (end - first).div_ceil(self.blocks_per_batch);
+        let total = batches * addresses.len() as u64;
+        let mut done = 0;
+        for (wallet_name, address) in &addresses {
+            let Ok(parsed) = address.parse::<Address>() else {
+                warn!("reconciliation: {} has unreadable address {} on {}", wallet_name, address, network);
+                continue;
+            };
+            let scope = LedgerScope { wallet_name, network, address, source: source.strategy() };
+            let mut from = first;
+            while from < end {
+                self.check_cancelled()?;
+                let to = (from + self.blocks_per_batch).min(end) - 1;
+                let transfers = source.transfers(network, parsed, from, to).await?;
+                self.check_transfers(&scope, parsed, &transfers).await?;
+                from = to + 1;
+                done += 1;
+                self.report(network, "chain", done, total);
+            }
+        }
+        Ok(())
+    }
+
+    /// Looks up every transaction among `transfers` (one batch of one address).
+    async fn check_transfers(
+        &mut self,
+        scope: &LedgerScope<'_>,
+        address: Address,
+        transfers: &[RawTransfer],
+    ) -> Result<(), ReconcileError> {
+        let mut by_hash: Vec<(H256, Vec<&RawTransfer>)> = Vec::new();
+        for transfer in transfers.iter().filter(|t| (self.range.from..self.range.to).contains(&t.timestamp)) {
+            match by_hash.iter_mut().find(|(hash, _)| *hash == transfer.tx_hash) {
+                Some((_, group)) => group.push(transfer),
+                None => by_hash.push((transfer.tx_hash, vec![transfer])),
+            }
+        }
+        for (hash, group) in by_hash {
+            self.totals.scanned += 1;
+            let tx_hash = format!("{:?}", hash);
+            if self.ctx.storage.transaction_for_wallet(scope.wallet_name, &tx_hash).await?.is_some() {
+                continue;
+            }
+            let entries: Vec<_> = group.iter().map(|t| normalize(address, t)).collect();
+            let block = group[0].block_number;
+            self.discrepancies.push(Discrepancy {
+                kind: DiscrepancyKind::MissingRecord,
+                network: scope.network.to_string(),
+                wallet_id: scope.wallet_name.to_string(),
+                tx_hash: tx_hash.clone(),
+                transaction_id: None,
+                recorded: None,
+                onchain: Some(format!("block {}", block)),
+                detail: format!("{} transfer(s) of {} in block {} have no record", entries.len(), scope.address, block),
+                correctable: true,
+                applied: false,
+            });
+            let index = self.discrepancies.len() - 1;
+            if self.apply {
+                self.ctx.storage.record_onchain_transfers(scope, &entries).await?;
+                // entries stored earlier without their record stay reported
+                if self.ctx.storage.transaction_for_wallet(scope.wallet_name, &tx_hash).await?.is_some() {
+                    self.applied(index).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Lowest block in `0..=head + 1` mined at or after `at`
+    async fn first_block_at(&self, network: &str, head: u64, at: i64) -> Result<u64, ReconcileError> {
+        let (mut low, mut high) = (0, head + 1);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let mined_at = self
+                .ctx
+                .rpc
+                .block_with_transactions(network, mid)
+                .await?
+                .map_or(i64::MAX, |block| block.timestamp.min(U256::from(i64::MAX as u64)).as_u64() as i64);
+            if mined_at >= at {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        Ok(low)
+    }
+
+    /// Adds a finding about a stored record; returns its index.
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        &mut self,
+        record: &TransactionRecord,
+        wallet_name: &str,
+        kind: DiscrepancyKind,
+        recorded: Option<String>,
+        onchain: Option<String>,
+        detail: String,
+        correctable: bool,
+    ) -> usize {
+        self.discrepancies.push(Discrepancy {
+            kind,
+            network: record.network.clone(),
+            wallet_id: wallet_name.to_string(),
+            tx_hash: record.tx_hash.clone(),
+            transaction_id: Some(record.id.clone()),
+            recorded,
+            onchain,
+            detail,
+            correctable,
+            applied: false,
+        });
+        self.discrepancies.len() - 1
+    }
+
+    /// Marks a finding corrected and audits the correction.
+    async fn applied(&mut self, index: usize) {
+        let discrepancy = &mut self.discrepancies[index];
+        discrepancy.applied = true;
+        let details = serde_json::json!({
+            "kind": discrepancy.kind,
+            "network": discrepancy.network,
+            "tx_hash": discrepancy.tx_hash,
+            "transaction_id": discrepancy.transaction_id,
+            "recorded": discrepancy.recorded,
+            "onchain": discrepancy.onchain,
+            "requested_by": self.ctx.requested_by,
+        });
+        let (wallet_id, details) = (discrepancy.wallet_id.clone(), details.to_string());
+        let storage = self.ctx.storage;
+        if let Err(e) = storage.log_action(&wallet_id, "reconciliation.corrected", &details, None, None).await {
+            warn!("failed to audit reconciliation correction of {}: {}", wallet_id, e);
+        }
+    }
+}
+
+fn same_address(recorded: &str, address: Address) -> bool {
+    recorded.trim().parse::<Address>().is_ok_and(|a| a == address)
+}
+
+/// Which sides of the mined transaction `address` is on: the sender (who
+/// pays), or the recipient of its value or of one of its token transfers.
+fn receipt_sides(receipt: &TransactionReceipt, address: Address) -> Sides {
+    let transfers: Vec<RawTransfer> =
+        receipt.logs.iter().filter_map(|log| RawTransfer::token_transfer(log, 0)).collect();
+    Sides {
+        sent: receipt.from == address,
+        received: receipt.to == Some(address) || transfers.iter().any(|t| t.to == Some(address)),
+    }
+}
@@ -28,6 +28,7 @@
 
 use super::WalletManager;
 use crate::core::errors::WalletError;
+use crate::crypto::message_signing::{MessageScheme, MessageSigner};
 use tracing::info;
 
 impl WalletManager {
@@ -36,9 +37,9 @@ impl WalletManager {
     /// # 算法流程
     /// 1. 解密master_key
     /// 2. 创建LocalWallet
-    /// 3. 构建TransactionRequest
-    /// 4. 估算Gas
-    /// 5. Sign transaction
+    /// 3. 构建 EIP-1559（type-2）[`Tx`](crate::core::domain::Tx)
+    /// 4. 由 gas price 推出 max fee / priority fee
+    /// 5. 经 [`Self::sign_ethereum_tx`] 同一路径sign
     /// 6. 返回RLP编码的Sign transaction
     ///
     /// # Arguments
@@ -73,27 +74,9 @@ impl WalletManager {
     ) -> Result<Vec<u8>, WalletError> {
         info!("Signing Ethereum transaction for wallet: {}", wallet_name);
         
-        // Step 1: fetchwallet数据
-        let wallet = self.get_wallet_by_name(wallet_name).await?
-            .ok_or_else(|| WalletError::NotFoundError(
-                format!("Wallet '{}' not found", wallet_name)
-            ))?;
-        
-        // Step 2: 解密master_key
-        let master_key = self.decrypt_master_key(&wallet, password).await?;
-        
-        // Step 3: 创建LocalWallet
-        use ethers::signers::{LocalWallet, Signer};
-        use ethers::core::k256::ecdsa::SigningKey;
-        use k256::SecretKey;
-        
-        let secret_key = SecretKey::from_slice(&master_key)
-            .map_err(|e| WalletError::CryptoError(format!("Invalid secret key: {}", e)))?;
-        
-        let signing_key = SigningKey::from(secret_key);
-        let signer = LocalWallet::from(signing_key);
-        
-        info!("✅ Signer created, address: {:?}", signer.address());
+        // Step 1-3: 解密master_key并创建LocalWallet
+        use ethers::signers::Signer;
+        let signer = self.ethereum_signer(wallet_name, password).await?;
         
         // Step 4: fetchRPC Provider
         use ethers::prelude::{Provider, Http};
@@ -104,7 +87,7 @@ impl WalletManager {
             .map_err(|e| WalletError::NetworkError(format!("Failed to connect to RPC: {}", e)))?;
         
         // Step 5: 构建transaction
-        use ethers::types::{TransactionRequest, Address};
+        use ethers::types::Address;
         use std::str::FromStr;
         
         let to_address = Address::from_str(to)
@@ -123,45 +106,121 @@ impl WalletManager {
             _ => 1,
         };
         
-        // 连接signer到provider
-        let client = signer.with_chain_id(chain_id);
-        
         // Step 7: fetchNonce
-        let nonce = provider.get_transaction_count(client.address(), None).await
+        let nonce = provider.get_transaction_count(signer.address(), None).await
             .map_err(|e| WalletError::NetworkError(format!("Failed to get nonce: {}", e)))?;
         
         info!("Transaction nonce: {}", nonce);
         
-        // Step 8: 构建完整的transaction
-        let mut tx = TransactionRequest::new()
-            .to(to_address)
-            .value(amount_wei)
-            .nonce(nonce)
-            .chain_id(chain_id);
-        
-        // Step 9: 估算Gas和Gas Price
+        // Step 8: 由 gas price 推出 EIP-1559 费用
         let gas_price = provider.get_gas_price().await
             .map_err(|e| WalletError::NetworkError(format!("Failed to get gas price: {}", e)))?;
-        
-        tx = tx.gas_price(gas_price).gas(21000); // 标准转账
+        let (max_fee_per_gas, max_priority_fee_per_gas) =
+            crate::blockchain::ethereum::eip1559_fees_from_gas_price(gas_price);
         
         info!("Gas price: {} gwei", gas_price.as_u64() / 1_000_000_000);
         
-        // Step 10: 转换为TypedTransaction并sign
-        use ethers::types::transaction::eip2718::TypedTransaction;
-        let typed_tx: TypedTransaction = tx.into();
-        
-        let signature = client.sign_transaction(&typed_tx).await
-            .map_err(|e| WalletError::CryptoError(format!("Failed to sign transaction: {}", e)))?;
-        
-        // Step 11: 编码为RLP
-        let signed_tx = typed_tx.rlp_signed(&signature);
+        // Step 9: 构建 type-2 transaction（标准转账 21000 gas）
+        let mut tx = crate::core::domain::Tx::new_native_transfer(
+            &format!("{:#x}", to_address),
+            &amount_wei.to_string(),
+            network,
+        )
+        .with_eip1559_fees(&max_fee_per_gas.to_string(), &max_priority_fee_per_gas.to_string())
+        .with_nonce(nonce.as_u64())
+        .with_chain_id(chain_id);
+        tx.gas_limit = Some("21000".to_string());
         
-        info!("✅ Transaction signed successfully, size: {} bytes", signed_tx.len());
-        
-        Ok(signed_tx.to_vec())
+        // Step 10: sign
+        self.sign_tx_with(signer, &tx)
     }
     
+    /// sign一笔已填好 gas、nonce 与 chain id 的 [`Tx`]
+    ///
+    /// 设置了 `max_fee_per_gas` / `max_priority_fee_per_gas` 时构建 EIP-1559（type-2）transaction，
+    /// 否则按 `gas_price` 构建 Legacy transaction。
+    ///
+    /// # Returns
+    /// * `Ok(Vec<u8>)` - 编码后的Sign transaction
+    #[cfg(feature = "ethereum")]
+    pub async fn sign_ethereum_tx(
+        &self,
+        wallet_name: &str,
+        tx: &crate::core::domain::Tx,
+        password: &str,
+    ) -> Result<Vec<u8>, WalletError> {
+        let signer = self.ethereum_signer(wallet_name, password).await?;
+        self.sign_tx_with(signer, tx)
+    }
+
+    /// 用已解密的 signer sign [`Tx`]
+    #[cfg(feature = "ethereum")]
+    fn sign_tx_with(
+        &self,
+        signer: ethers::signers::LocalWallet,
+        tx: &crate::core::domain::Tx,
+    ) -> Result<Vec<u8>, WalletError> {
+        use ethers::signers::Signer;
+
+        // validate 保证 chain id 存在，且 gas_price 与 EIP-1559 字段未混用
+        tx.validate().map_err(|e| WalletError::ValidationError(e.to_string()))?;
+        let typed_tx = tx.to_typed_transaction()
+            .map_err(|e| WalletError::ValidationError(e.to_string()))?;
+        let signer = signer.with_chain_id(tx.chain_id.unwrap_or_default());
+        let signed_tx =
+            crate::core::rlp::tx::sign_transaction(self.config.security.tx_encoding, &signer, &typed_tx)?;
+
+        info!(
+            "✅ Transaction signed ({}), size: {} bytes",
+            if tx.is_eip1559() { "EIP-1559" } else { "legacy" },
+            signed_tx.len()
+        );
+        Ok(signed_tx.to_vec())
+    }
+
+    /// 解密wallet私钥并创建 [`LocalWallet`]（不设置 chain id）
+    ///
+    /// 供需要自行控制sign与广播时机的调用方使用（如sign意图日志）。
+    pub async fn ethereum_signer(
+        &self,
+        wallet_name: &str,
+        password: &str,
+    ) -> Result<ethers::signers::LocalWallet, WalletError> {
+        use ethers::core::k256::ecdsa::SigningKey;
+        use ethers::signers::{LocalWallet, Signer};
+        use k256::SecretKey;
+
+        let wallet = self.get_wallet_by_name(wallet_name).await?
+            .ok_or_else(|| WalletError::NotFoundError(
+                format!("Wallet '{}' not found", wallet_name)
+            ))?;
+        let master_key = self.decrypt_signing_key(&wallet, password).await?;
+
+        let secret_key = SecretKey::from_slice(&master_key)
+            .map_err(|e| WalletError::CryptoError(format!("Invalid secret key: {}", e)))?;
+        let signer = LocalWallet::from(SigningKey::from(secret_key));
+
+        info!("✅ Signer created, address: {:?}", signer.address());
+        Ok(signer)
+    }
+
+    /// 解密wallet私钥并创建链下消息sign器
+    ///
+    /// EVM network使用 EIP-191，`btc` 使用 Bitcoin 消息sign（压缩公钥 P2PKH address）。
+    pub async fn message_signer(
+        &self,
+        wallet_name: &str,
+        password: &str,
+        network: &str,
+    ) -> Result<MessageSigner, WalletError> {
+        let wallet = self.get_wallet_by_name(wallet_name).await?
+            .ok_or_else(|| WalletError::NotFoundError(
+                format!("Wallet '{}' not found", wallet_name)
+            ))?;
+        let master_key = self.decrypt_signing_key(&wallet, password).await?;
+        MessageSigner::new(MessageScheme::for_network(network), &master_key)
+    }
+
     /// 广播Ethereumtransaction到区块链
     ///
     /// # Arguments
@@ -199,7 +258,7 @@ impl WalletManager {
     }
     
     /// fetchnetwork的RPC URL
-    fn get_rpc_url(&self, network: &str) -> Result<&str, WalletError> {
+    pub(crate) fn get_rpc_url(&self, network: &str) -> Result<&str, WalletError> {
         let url = self.config.blockchain.networks.get(network)
             .map(|n| n.rpc_url.as_str())
             .or({
@@ -228,5 +287,97 @@ mod tests {
         // 测试RPC URLfetch逻辑
         // 需要mock WalletManager
     }
+
+    use super::WalletManager;
+    use crate::core::config::WalletConfig;
+    use crate::security::memory_protection::{assert_no_secret_leaks, take_secret_escapes};
+
+    const PASSWORD: &str = "Le4kAudit!wallet";
+
+    async fn manager_with_wallet(name: &str) -> WalletManager {
+        let mut config = WalletConfig::default();
+        config.security.pbkdf2_iterations = 1_000;
+        let wm = WalletManager::new(&config).await.unwrap();
+        wm.create_wallet(name, PASSWORD, false).await.unwrap();
+        wm
+    }
+
+    #[tokio::test]
+    async fn test_send_signing_does_not_leak_master_key() {
+        use ethers::signers::Signer;
+        use ethers::types::transaction::eip2718::TypedTransaction;
+        use ethers::types::{Address, TransactionRequest};
+
+        let wm = manager_with_wallet("leak_send").await;
+        let signer = wm.ethereum_signer("leak_send", PASSWORD).await.unwrap().with_chain_id(1u64);
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(Address::repeat_byte(0x22))
+            .value(1u64)
+            .nonce(0u64)
+            .gas(21_000u64)
+            .gas_price(1u64)
+            .chain_id(1u64)
+            .into();
+        signer.sign_transaction(&tx).await.unwrap();
+        assert_no_secret_leaks();
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[tokio::test]
+    async fn test_sign_ethereum_tx_builds_type_2_when_fees_are_set() {
+        use crate::core::domain::Tx;
+        use ethers::types::transaction::eip2718::TypedTransaction;
+        use ethers::utils::rlp::Rlp;
+
+        let wm = manager_with_wallet("eip1559_send").await;
+        let tx = Tx::new_native_transfer("0x742d35Cc6634C0532925a3b844Bc454e4438f44e", "1000", "eth")
+            .with_nonce(3)
+            .with_chain_id(1);
+
+        let mut type2 = tx.clone().with_eip1559_fees("30000000000", "2000000000");
+        type2.gas_limit = Some("21000".to_string());
+        let raw = wm.sign_ethereum_tx("eip1559_send", &type2, PASSWORD).await.unwrap();
+        assert_eq!(raw[0], 0x02);
+        let (decoded, _) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        let TypedTransaction::Eip1559(request) = decoded else { panic!("expected a type-2 transaction") };
+        assert_eq!(request.max_fee_per_gas, Some(30_000_000_000u64.into()));
+        assert_eq!(request.max_priority_fee_per_gas, Some(2_000_000_000u64.into()));
+
+        // 无 EIP-1559 字段时仍是 Legacy transaction（RLP 列表前缀）
+        let legacy = tx.clone().with_gas("20000000000", "21000");
+        let raw = wm.sign_ethereum_tx("eip1559_send", &legacy, PASSWORD).await.unwrap();
+        assert!(raw[0] >= 0xc0);
+
+        // 混用两种 gas 参数在sign前被拒绝
+        let mixed = legacy.with_eip1559_fees("30000000000", "2000000000");
+        assert!(wm.sign_ethereum_tx("eip1559_send", &mixed, PASSWORD).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_whitelisted_and_zeroizing_copies_are_not_leaks() {
+        let wm = manager_with_wallet("leak_allowed").await;
+        let wallet = wm.get_wallet_by_name("leak_allowed").await.unwrap().unwrap();
+        let master_key = wm.decrypt_master_key(&wallet, PASSWORD).await.unwrap();
+
+        let _copy = master_key.to_secret();
+        let _raw = master_key.to_vec_allowed("handed to a zeroizing caller");
+        // 白名单逃逸仍然被记录，只是不触发失败（assert_no_secret_leaks 会清空记录，不能先调用）
+        let escapes = take_secret_escapes();
+        assert_eq!(escapes.len(), 1);
+        assert_eq!(escapes[0].allowed, Some("handed to a zeroizing caller"));
+    }
+
+    /// 故意泄漏的 fixture：证明检测器确实会触发
+    #[tokio::test]
+    #[should_panic(expected = "secret escape")]
+    async fn test_leaky_fixture_is_detected() {
+        let wm = manager_with_wallet("leak_fixture").await;
+        let wallet = wm.get_wallet_by_name("leak_fixture").await.unwrap().unwrap();
+        let master_key = wm.decrypt_master_key(&wallet, PASSWORD).await.unwrap();
+
+        let leaked: Vec<u8> = master_key.to_vec();
+        assert_eq!(leaked.len(), 32);
+        assert_no_secret_leaks();
+    }
 }
 
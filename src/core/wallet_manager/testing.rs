@@ -65,6 +65,8 @@ impl WalletManager {
             wallets: Arc::new(RwLock::new(HashMap::new())),
             nonce_tracker: Arc::new(RwLock::new(HashMap::new())),
             bridge_transactions: Arc::new(RwLock::new(HashMap::new())),
+            clock: crate::core::clock::system_clock(),
+            ids: crate::core::ids::random_ids(),
             // Ethereum 暂时禁用
             // #[cfg(feature = "ethereum")]
             // ethereum_clients: Arc::new(RwLock::new(HashMap::new())),
@@ -5,10 +5,15 @@
 use super::WalletManager;
 use crate::core::errors::WalletError;
 use crate::core::wallet_info::SecureWalletData;
+use crate::security::env_manager::config_secrets::redact_url;
+use crate::security::memory_protection::TaintedBytes;
 use crate::security::password_validator::{validate_password, PasswordPolicy};
 use tracing::info;
 use zeroize::Zeroizing;
 
+/// `(ciphertext, salt, nonce)`
+type EncryptedKey = (Vec<u8>, Vec<u8>, Vec<u8>);
+
 impl WalletManager {
     /// Decrypt wallet's master key using password
     ///
@@ -17,18 +22,19 @@ impl WalletManager {
     /// * `password` - User password for key derivation
     ///
     /// # Returns
-    /// * `Ok(Zeroizing<Vec<u8>>)` - Decrypted master key (auto-zeroized on drop)
+    /// * `Ok(TaintedBytes)` - Decrypted master key (auto-zeroized on drop; copies
+    ///   into plain `Vec<u8>` are recorded in test builds)
     ///
     /// # Security
     /// - Uses AES-256-GCM for decryption
     /// - Derives decryption key from password using PBKDF2
-    /// - Returns Zeroizing wrapper for automatic memory cleanup
+    /// - Returns a zeroizing [`TaintedBytes`] so leaked copies fail the leak audit
     #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x but required here
     pub(super) async fn decrypt_master_key(
         &self,
         wallet_data: &SecureWalletData,
         password: &str,
-    ) -> Result<Zeroizing<Vec<u8>>, WalletError> {
+    ) -> Result<TaintedBytes, WalletError> {
         use aes_gcm::{
             aead::{Aead, KeyInit},
             Aes256Gcm, Nonce,
@@ -37,6 +43,10 @@ impl WalletManager {
         use sha2::Sha256;
         use zeroize::Zeroizing as ZeroizingVec;
         
+        // 0. 密钥后端超过有效期未确认主密钥、或主密钥轮换未完成时拒绝sign
+        crate::security::env_manager::secret_backend::ensure_master_key()
+            .map_err(|e| WalletError::InternalError(e.to_string()))?;
+        
         // 1. Validate password strength using default policy
         let policy = PasswordPolicy::default();
         validate_password(password, &policy)?;
@@ -81,7 +91,43 @@ impl WalletManager {
         }
         
         info!("✅ Master key decrypted successfully");
-        Ok(ZeroizingVec::new(plaintext))
+        Ok(TaintedBytes::new(ZeroizingVec::new(plaintext), "wallet master key"))
+    }
+
+    /// Encrypt a 32-byte key for storage (inverse of `decrypt_master_key`)
+    ///
+    /// # Returns
+    /// * `(ciphertext, salt, nonce)` - Fields for `SecureWalletData`
+    pub(super) fn encrypt_master_key(
+        &self,
+        master_key: &[u8],
+        password: &str,
+    ) -> Result<EncryptedKey, WalletError> {
+        use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit};
+        use pbkdf2::pbkdf2_hmac;
+        use rand_core::RngCore;
+        use sha2::Sha256;
+
+        let mut salt = [0u8; 32];
+        rand_core::OsRng.fill_bytes(&mut salt);
+        let mut nonce_bytes = [0u8; 12];
+        rand_core::OsRng.fill_bytes(&mut nonce_bytes);
+
+        let mut key_bytes = Zeroizing::new([0u8; 32]);
+        pbkdf2_hmac::<Sha256>(
+            password.as_bytes(),
+            &salt,
+            self.config.security.pbkdf2_iterations,
+            &mut *key_bytes,
+        );
+
+        let cipher = Aes256Gcm::new_from_slice(&*key_bytes)
+            .map_err(|_| WalletError::CryptoError("Failed to create AES cipher".into()))?;
+        let ciphertext = cipher
+            .encrypt(&aes_gcm::Nonce::from(nonce_bytes), master_key)
+            .map_err(|_| WalletError::CryptoError("Failed to encrypt master key".into()))?;
+
+        Ok((ciphertext, salt.to_vec(), nonce_bytes.to_vec()))
     }
 
     /// Send transaction (requires password to decrypt private key)
@@ -114,6 +160,7 @@ impl WalletManager {
             .get_wallet_by_name(wallet_name)
             .await?
             .ok_or_else(|| WalletError::NotFoundError(format!("Wallet not found: {}", wallet_name)))?;
+        self.ensure_network_allowed(wallet_name, network)?;
 
         // Route transaction to appropriate blockchain network
         match network {
@@ -142,7 +189,10 @@ impl WalletManager {
         network: &str,
         password: &str,
     ) -> Result<String, WalletError> {
-        use ethers::prelude::{Provider, Http, Middleware, TransactionRequest, Signer, SignerMiddleware};
+        use crate::blockchain::ethereum::EthereumClient;
+        use crate::blockchain::traits::BlockchainClient;
+        use crate::core::domain::PrivateKey;
+        use ethers::prelude::{Provider, Http, Middleware, Signer};
         use ethers::types::Address;
         use ethers::utils::parse_ether;
         use ethers::signers::{LocalWallet, Wallet};
@@ -157,7 +207,7 @@ impl WalletManager {
         }
         
         // 2. Validate password and decrypt private key
-        let private_key = self.decrypt_master_key(wallet_data, password).await?;
+        let private_key = self.decrypt_signing_key(wallet_data, password).await?;
         
         // 3. Create LocalWallet from decrypted private key
         let wallet: LocalWallet = Wallet::from_bytes(&private_key)
@@ -176,18 +226,16 @@ impl WalletManager {
                 _ => "https://eth.llamarpc.com"
             });
         
-        info!("Connecting to RPC: {}", rpc_url);
+        info!("Connecting to RPC: {}", redact_url(rpc_url));
         
         // 6. Create provider instance
         let provider = Provider::<Http>::try_from(rpc_url)
             .map_err(|e| WalletError::NetworkError(format!("Failed to connect to RPC: {}", e)))?;
         
-        // 7. Create signer middleware with chain ID
+        // 7. Bind the provider to the network's chain ID
         let chain_id = provider.get_chainid().await
             .map_err(|e| WalletError::NetworkError(format!("Failed to get chain_id: {}", e)))?;
-        
-        let wallet_with_chain = wallet.with_chain_id(chain_id.as_u64());
-        let client = SignerMiddleware::new(provider.clone(), wallet_with_chain);
+        let client = EthereumClient::new_with_provider_and_chain(provider, network, chain_id.as_u64());
         
         // 8. Parse recipient address
         let to: Address = to_address.parse()
@@ -199,23 +247,73 @@ impl WalletManager {
         
         info!("Building transaction: to={}, value={} wei", to, value);
         
-        // 10. Build transaction request
-        let tx = TransactionRequest::new()
-            .to(to)
-            .value(value);
-        
-        // 11. Sign and broadcast transaction
+        // 10. Sign and broadcast as an EIP-1559 (type-2) transaction
         info!("Signing and broadcasting transaction...");
-        let pending_tx = client.send_transaction(tx, None).await
-            .map_err(|e| WalletError::NetworkError(format!("Failed to send transaction: {}", e)))?;
-        
-        let tx_hash = format!("{:?}", pending_tx.tx_hash());
+        let private_key = PrivateKey::try_from_slice(&private_key)
+            .map_err(|e| WalletError::CryptoError(format!("Invalid private key: {}", e)))?;
+        let tx_hash = client.send_transaction(&private_key, to_address, amount).await?;
         info!("✅ Transaction broadcasted to network: tx_hash={}", tx_hash);
         
-        // 12. Return transaction hash
+        // 11. Return transaction hash
         Ok(tx_hash)
     }
 
+    /// Sign and broadcast a contract call from `wallet_name` (e.g. a relayer
+    /// submitting a forwarder `execute`).
+    ///
+    /// # Arguments
+    /// * `to` - Contract address
+    /// * `value` - Wei attached to the call
+    /// * `data` - ABI-encoded calldata
+    /// * `gas_limit` - Gas limit; not estimated, the caller knows the callee
+    ///
+    /// # Returns
+    /// * `Ok(String)` - Transaction hash
+    #[cfg(feature = "ethereum")]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_contract_call(
+        &self,
+        wallet_name: &str,
+        to: ethers::types::Address,
+        value: ethers::types::U256,
+        data: Vec<u8>,
+        gas_limit: u64,
+        network: &str,
+        password: &str,
+    ) -> Result<String, WalletError> {
+        use ethers::prelude::{Http, Middleware, Provider, Signer, SignerMiddleware, TransactionRequest};
+        use ethers::signers::{LocalWallet, Wallet};
+
+        let wallet_data = self
+            .get_wallet_by_name(wallet_name)
+            .await?
+            .ok_or_else(|| WalletError::NotFoundError(format!("Wallet not found: {}", wallet_name)))?;
+        self.ensure_network_allowed(wallet_name, network)?;
+        if wallet_data.encrypted_master_key.is_empty() {
+            return Err(WalletError::CryptoError("Wallet has no encrypted private key data".to_string()));
+        }
+        let private_key = self.decrypt_signing_key(&wallet_data, password).await?;
+        let wallet: LocalWallet = Wallet::from_bytes(&private_key)
+            .map_err(|e| WalletError::CryptoError(format!("Invalid private key: {}", e)))?;
+
+        let provider = Provider::<Http>::try_from(self.get_rpc_url(network)?)
+            .map_err(|e| WalletError::NetworkError(format!("Failed to connect to RPC: {}", e)))?;
+        let chain_id = provider
+            .get_chainid()
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Failed to get chain_id: {}", e)))?;
+        let client = SignerMiddleware::new(provider, wallet.with_chain_id(chain_id.as_u64()));
+
+        let tx = TransactionRequest::new().to(to).value(value).data(data).gas(gas_limit);
+        let pending_tx = client
+            .send_transaction(tx, None)
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Failed to send transaction: {}", e)))?;
+
+        let tx_hash = format!("{:?}", pending_tx.tx_hash());
+        info!("✅ Contract call broadcast: wallet={}, to={:?}, tx_hash={}", wallet_name, to, tx_hash);
+        Ok(tx_hash)
+    }
 
     /// Send multi-signature transaction
     ///
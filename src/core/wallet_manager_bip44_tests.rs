@@ -4,6 +4,7 @@ use tokio;
 
 use crate::core::config::WalletConfig;
 use crate::core::wallet_info::SecureWalletData;
+use crate::core::wallet_manager::derivation::DerivationPath;
 use crate::core::wallet_manager::WalletManager;
 use crate::storage::WalletStorage;
 use base64::Engine;
@@ -38,12 +39,13 @@ async fn print_eth_bip44_from_zero_seed() {
     );
 
     let cfg = WalletConfig::default();
+    let path = DerivationPath::parse(&cfg.derivation.path).expect("default path");
     let wm = WalletManager::new_with_storage(cfg, storage).await.expect("wm init");
 
     let seed = zero_seed32();
 
     // Derive private key and address for Ethereum default BIP44 path m/44'/60'/0'/0/0
-    let addr = wm.derive_address(&seed, "eth").expect("derive addr");
+    let addr = wm.derive_address(&seed, "eth", Some(&path)).expect("derive addr");
     println!("ETH m/44'/60'/0'/0/0 from zero seed -> addr: {}", addr);
 
     // Non-asserting probe test; will be replaced with fixed-vector assertion
@@ -58,13 +60,18 @@ async fn eth_derivation_override_affects_address() {
     let mut cfg = WalletConfig::default();
     // ✅ 使用新的path字符串格式（account=1, change=0, index=5）
     cfg.derivation.path = "m/44'/60'/1'/0/5".to_string();
+    let path = DerivationPath::parse(&cfg.derivation.path).expect("override path");
     let wm = WalletManager::new_with_storage(cfg, storage).await.expect("wm init");
 
     let seed = zero_seed32();
-    let addr = wm.derive_address(&seed, "eth").expect("derive addr");
+    let addr = wm.derive_address(&seed, "eth", Some(&path)).expect("derive addr");
     // Deterministic but not hard-coded; basic sanity
     assert!(addr.starts_with("0x"));
     assert_eq!(addr.len(), 42);
+
+    let default_path = DerivationPath::parse("m/44'/60'/0'/0/0").unwrap();
+    assert_ne!(addr, wm.derive_address(&seed, "eth", Some(&default_path)).unwrap());
+    assert_ne!(addr, wm.derive_address(&seed, "eth", None).unwrap());
 }
 
 #[tokio::test]
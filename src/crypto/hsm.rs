@@ -229,7 +229,7 @@ impl HSMManager {
         Ok(HSMMemoryStats {
             total_regions,
             total_memory_bytes: total_memory,
-            average_region_size: if total_regions > 0 { total_memory / total_regions } else { 0 },
+            average_region_size: total_memory.checked_div(total_regions).unwrap_or(0),
         })
     }
 
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use argon2::{Algorithm, Argon2, Version};
 use hkdf::Hkdf;
 use pbkdf2::pbkdf2_hmac;
 use scrypt::Params;
@@ -10,6 +11,8 @@ use zeroize::{Zeroize, Zeroizing};
 pub enum KDFAlgorithm {
     PBKDF2 { iterations: u32 },
     Scrypt { n: u32, r: u32, p: u32 },
+    /// `m_cost` in KiB
+    Argon2id { m_cost: u32, t_cost: u32, p_cost: u32 },
     HKDF,
 }
 pub struct KeyDerivation {
@@ -30,6 +33,10 @@ impl KeyDerivation {
         Self::new(KDFAlgorithm::Scrypt { n, r, p })
     }
 
+    pub fn argon2id(m_cost: u32, t_cost: u32, p_cost: u32) -> Self {
+        Self::new(KDFAlgorithm::Argon2id { m_cost, t_cost, p_cost })
+    }
+
     pub fn hkdf() -> Self {
         Self::new(KDFAlgorithm::HKDF)
     }
@@ -49,6 +56,9 @@ impl KeyDerivation {
             KDFAlgorithm::Scrypt { n, r, p } => {
                 self.derive_scrypt(password, salt, *n, *r, *p, key_length)
             }
+            KDFAlgorithm::Argon2id { m_cost, t_cost, p_cost } => {
+                self.derive_argon2id(password, salt, *m_cost, *t_cost, *p_cost, key_length)
+            }
             KDFAlgorithm::HKDF => self.derive_hkdf(password, salt, key_length),
         }
     }
@@ -91,6 +101,28 @@ impl KeyDerivation {
         Ok(key)
     }
 
+    fn derive_argon2id(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+        key_length: usize,
+    ) -> Result<Zeroizing<Vec<u8>>> {
+        debug!("Using Argon2id with parameters m={}KiB, t={}, p={}", m_cost, t_cost, p_cost);
+
+        let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(key_length))
+            .map_err(|e| anyhow::anyhow!("Invalid Argon2 parameters: {}", e))?;
+
+        let mut key = Zeroizing::new(vec![0u8; key_length]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password, salt, &mut key)
+            .map_err(|e| anyhow::anyhow!("Argon2 derivation failed: {}", e))?;
+
+        Ok(key)
+    }
+
     fn derive_hkdf(
         &self,
         input_key_material: &[u8],
@@ -217,6 +249,21 @@ mod tests {
         assert_eq!(key, key2);
     }
 
+    #[test]
+    fn test_argon2id_derivation() {
+        let kdf = KeyDerivation::argon2id(1024, 1, 1);
+        let password = b"test_password";
+        let salt = b"test_salt_123";
+
+        let key = kdf.derive_key(password, salt, 64).unwrap();
+        assert_eq!(key.len(), 64);
+        assert_eq!(key, kdf.derive_key(password, salt, 64).unwrap());
+        assert_ne!(key, KeyDerivation::argon2id(2048, 1, 1).derive_key(password, salt, 64).unwrap());
+
+        // salts shorter than 8 bytes are rejected by argon2
+        assert!(kdf.derive_key(password, b"short", 32).is_err());
+    }
+
     #[test]
     fn test_hkdf_derivation() {
         let kdf = KeyDerivation::hkdf();
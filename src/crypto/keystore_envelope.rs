@@ -0,0 +1,251 @@
+//! Signed keystore envelopes for exchanging key vaults with partner systems.
+//!
+//! An envelope wraps an unmodified keystore V3 document with a `meta` section
+//! and a signature by the sender's transport key:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "keystore": { "address": "...", "crypto": { ... }, "id": "...", "version": 3 },
+//!   "meta": { "tool": "...", "origin": "...", "address": "0xAbC...", "label": "..." },
+//!   "signature": { "scheme": "secp256k1-sha256", "sender": "02...", "value": "..." }
+//! }
+//! ```
+//!
+//! `meta.address` is EIP-55 checksummed and `label` is optional. `sender` is
+//! the SEC1 compressed public key of the transport key and `value` the 64-byte
+//! `r || s` ECDSA signature (low-S), both hex. The signed message is the
+//! [canonical JSON](crate::util::canonical_json) of the document as received
+//! with the `signature` member removed, hashed with SHA-256. Members this
+//! module does not know about are therefore covered by the signature too, and
+//! a recipient never has to re-serialize a keystore the way the sender did.
+
+use ethers::core::k256::ecdsa::signature::{Signer as _, Verifier as _};
+use ethers::core::k256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use ethers::types::Address;
+use ethers::utils::to_checksum;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::core::errors::WalletError;
+use crate::crypto::keystore_v3::KeystoreV3;
+use crate::util::canonical_json;
+
+pub const ENVELOPE_VERSION: u64 = 1;
+pub const SIGNATURE_SCHEME: &str = "secp256k1-sha256";
+
+/// `meta` members every envelope must carry.
+const REQUIRED_META: [&str; 3] = ["tool", "origin", "address"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum EnvelopeError {
+    #[error("Invalid keystore envelope: {0}")]
+    Malformed(String),
+    #[error("Invalid sender key: {0}")]
+    InvalidSenderKey(String),
+    #[error("Envelope meta is missing '{0}'")]
+    MissingMeta(&'static str),
+    #[error("Envelope was signed by {actual}, expected {expected}")]
+    SenderMismatch { expected: String, actual: String },
+    #[error("Envelope signature does not verify")]
+    BadSignature,
+    #[error(transparent)]
+    Wallet(#[from] WalletError),
+}
+
+impl EnvelopeError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            EnvelopeError::Malformed(_) => "INVALID_ENVELOPE",
+            EnvelopeError::InvalidSenderKey(_) => "INVALID_SENDER_KEY",
+            EnvelopeError::MissingMeta(_) => "ENVELOPE_META_MISSING",
+            EnvelopeError::SenderMismatch { .. } => "ENVELOPE_SENDER_MISMATCH",
+            EnvelopeError::BadSignature => "ENVELOPE_SIGNATURE_INVALID",
+            EnvelopeError::Wallet(_) => "KEYSTORE_FAILED",
+        }
+    }
+}
+
+/// Provenance of the enclosed key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvelopeMeta {
+    /// Software that wrote the envelope, e.g. `defi-hot-wallet/0.1.0`.
+    pub tool: String,
+    /// How the key came to exist, e.g. `bip32-master` or `imported`.
+    pub origin: String,
+    /// EIP-55 checksummed address of the key.
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvelopeSignature {
+    pub scheme: String,
+    /// Compressed SEC1 public key, hex.
+    pub sender: String,
+    /// `r || s`, hex.
+    pub value: String,
+}
+
+impl EnvelopeSignature {
+    /// Signs `document`; its `signature` member, if any, is not covered.
+    pub fn sign(document: &Value, key: &SigningKey) -> Self {
+        let signature: Signature = key.sign(signing_payload(document).as_bytes());
+        Self {
+            scheme: SIGNATURE_SCHEME.to_string(),
+            sender: sender_key(key.verifying_key()),
+            value: hex::encode(signature.to_bytes()),
+        }
+    }
+
+    pub fn verify(&self, document: &Value) -> Result<(), EnvelopeError> {
+        if self.scheme != SIGNATURE_SCHEME {
+            return Err(EnvelopeError::Malformed(format!("unsupported signature scheme '{}'", self.scheme)));
+        }
+        let sender = parse_sender_key(&self.sender)?;
+        let bytes = hex::decode(&self.value).map_err(|_| EnvelopeError::BadSignature)?;
+        // high-S signatures are rejected by the verifier as well
+        let signature = Signature::from_slice(&bytes).map_err(|_| EnvelopeError::BadSignature)?;
+        sender
+            .verify(signing_payload(document).as_bytes(), &signature)
+            .map_err(|_| EnvelopeError::BadSignature)
+    }
+}
+
+/// Hex of the compressed SEC1 encoding, the form `signature.sender` uses.
+pub fn sender_key(key: &VerifyingKey) -> String {
+    hex::encode(key.to_encoded_point(true).as_bytes())
+}
+
+/// Accepts compressed or uncompressed SEC1 hex, with or without `0x`.
+pub fn parse_sender_key(hex_key: &str) -> Result<VerifyingKey, EnvelopeError> {
+    let bytes = hex::decode(hex_key.trim().trim_start_matches("0x"))
+        .map_err(|e| EnvelopeError::InvalidSenderKey(e.to_string()))?;
+    VerifyingKey::from_sec1_bytes(&bytes).map_err(|_| EnvelopeError::InvalidSenderKey("not a secp256k1 point".into()))
+}
+
+/// The bytes that get signed: everything but `signature`, canonicalized.
+pub fn signing_payload(document: &Value) -> String {
+    let mut payload = document.clone();
+    if let Some(object) = payload.as_object_mut() {
+        object.remove("signature");
+    }
+    canonical_json::to_string(&payload)
+}
+
+/// An envelope as written by [`KeystoreEnvelope::seal`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreEnvelope {
+    pub version: u64,
+    pub keystore: KeystoreV3,
+    pub meta: EnvelopeMeta,
+    pub signature: EnvelopeSignature,
+}
+
+/// A verified envelope, ready for import.
+#[derive(Debug, Clone)]
+pub struct OpenedEnvelope {
+    /// Carries `meta.address` when the partner left the keystore's own
+    /// address out, so the V3 import still checks the decrypted key against it.
+    pub keystore: KeystoreV3,
+    pub meta: EnvelopeMeta,
+    /// Compressed sender key, hex.
+    pub sender: String,
+}
+
+impl KeystoreEnvelope {
+    pub fn seal(keystore: KeystoreV3, meta: EnvelopeMeta, key: &SigningKey) -> Self {
+        let document = serde_json::json!({
+            "version": ENVELOPE_VERSION,
+            "keystore": keystore,
+            "meta": meta,
+        });
+        let signature = EnvelopeSignature::sign(&document, key);
+        Self { version: ENVELOPE_VERSION, keystore, meta, signature }
+    }
+
+    pub fn to_json(&self) -> Result<String, WalletError> {
+        serde_json::to_string(self).map_err(|e| WalletError::SerializationError(e.to_string()))
+    }
+
+    /// Checks, in order: structure, that the signer is `expected_sender`, the
+    /// signature, the required meta fields, and that `meta.address` is
+    /// checksummed and agrees with the keystore. The keystore itself is not
+    /// decrypted here.
+    pub fn open(json: &str, expected_sender: &str) -> Result<OpenedEnvelope, EnvelopeError> {
+        let expected = parse_sender_key(expected_sender)?;
+        let mut document: Map<String, Value> =
+            serde_json::from_str(json).map_err(|e| EnvelopeError::Malformed(e.to_string()))?;
+        match document.get("version").and_then(Value::as_u64) {
+            Some(ENVELOPE_VERSION) => {}
+            other => {
+                return Err(EnvelopeError::Malformed(format!("unsupported envelope version {:?}", other)));
+            }
+        }
+        let signature: EnvelopeSignature = document
+            .remove("signature")
+            .ok_or_else(|| EnvelopeError::Malformed("missing signature".into()))
+            .and_then(|s| serde_json::from_value(s).map_err(|e| EnvelopeError::Malformed(e.to_string())))?;
+
+        let actual = parse_sender_key(&signature.sender)?;
+        if actual != expected {
+            return Err(EnvelopeError::SenderMismatch { expected: sender_key(&expected), actual: sender_key(&actual) });
+        }
+        let document = Value::Object(document);
+        signature.verify(&document)?;
+
+        let meta = parse_meta(document.get("meta"))?;
+        let keystore_json =
+            document.get("keystore").ok_or_else(|| EnvelopeError::Malformed("missing keystore".into()))?;
+        let mut keystore = KeystoreV3::from_json(&keystore_json.to_string())?;
+
+        let address: Address =
+            meta.address.parse().map_err(|_| EnvelopeError::Malformed("meta.address is not an address".into()))?;
+        if to_checksum(&address, None) != meta.address {
+            return Err(EnvelopeError::Malformed("meta.address is not EIP-55 checksummed".into()));
+        }
+        match &keystore.address {
+            Some(_) if !keystore.address_matches(&meta.address) => {
+                return Err(EnvelopeError::Malformed("meta.address does not match the keystore address".into()));
+            }
+            Some(_) => {}
+            None => keystore.address = Some(hex::encode(address.as_bytes())),
+        }
+        Ok(OpenedEnvelope { keystore, meta, sender: sender_key(&actual) })
+    }
+}
+
+fn parse_meta(meta: Option<&Value>) -> Result<EnvelopeMeta, EnvelopeError> {
+    let meta = meta.and_then(Value::as_object).ok_or(EnvelopeError::MissingMeta("meta"))?;
+    for field in REQUIRED_META {
+        if meta.get(field).and_then(Value::as_str).is_none_or(|v| v.trim().is_empty()) {
+            return Err(EnvelopeError::MissingMeta(field));
+        }
+    }
+    serde_json::from_value(Value::Object(meta.clone())).map_err(|e| EnvelopeError::Malformed(format!("meta: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_payload_ignores_signature_and_layout() {
+        let a: Value = serde_json::from_str(r#"{"meta": {"tool": "t"}, "signature": {"value": "00"}, "version": 1}"#)
+            .unwrap();
+        let b: Value = serde_json::from_str(r#"{"version":1,"meta":{"tool":"t"}}"#).unwrap();
+        assert_eq!(signing_payload(&a), r#"{"meta":{"tool":"t"},"version":1}"#);
+        assert_eq!(signing_payload(&a), signing_payload(&b));
+    }
+
+    #[test]
+    fn test_sender_key_accepts_both_encodings() {
+        let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let compressed = sender_key(key.verifying_key());
+        assert_eq!(compressed.len(), 66);
+        let uncompressed = hex::encode(key.verifying_key().to_encoded_point(false).as_bytes());
+        assert_eq!(parse_sender_key(&format!("0x{}", uncompressed)).unwrap(), *key.verifying_key());
+        assert!(matches!(parse_sender_key("02abcd"), Err(EnvelopeError::InvalidSenderKey(_))));
+    }
+}
@@ -0,0 +1,262 @@
+//! Ethereum keystore V3 (Web3 Secret Storage) encoding.
+//!
+//! Reads the `scrypt` and `pbkdf2` (hmac-sha256) KDF variants with
+//! `aes-128-ctr`, and writes scrypt keystores compatible with geth/MetaMask.
+//! The MAC is `keccak256(derived_key[16..32] ++ ciphertext)` and is checked
+//! before anything is decrypted.
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use crate::core::errors::WalletError;
+use crate::crypto::kdf::KeyDerivation;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// scrypt cost used for exported keystores (geth "standard" parameters).
+pub const DEFAULT_SCRYPT_N: u32 = 262_144;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const DKLEN: usize = 32;
+const CIPHER: &str = "aes-128-ctr";
+
+// Refuse KDF parameters that would tie up a worker for minutes or exhaust memory.
+const MAX_SCRYPT_N: u32 = 1 << 20;
+const MAX_SCRYPT_R: u32 = 32;
+const MAX_SCRYPT_P: u32 = 16;
+const MAX_PBKDF2_ROUNDS: u32 = 10_000_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreV3 {
+    /// Hex address without `0x`; optional in the spec, always written on export.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Older geth versions wrote the section as `Crypto`.
+    #[serde(alias = "Crypto")]
+    pub crypto: KeystoreCrypto,
+    pub id: String,
+    pub version: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreCrypto {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    pub mac: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KdfParams {
+    Scrypt { dklen: usize, n: u32, r: u32, p: u32, salt: String },
+    Pbkdf2 { c: u32, dklen: usize, prf: String, salt: String },
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>, WalletError> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| WalletError::DeserializationError(format!("keystore {}: {}", field, e)))
+}
+
+fn keccak_mac(derived_key: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(&derived_key[16..32]);
+    hasher.update(ciphertext);
+    hasher.finalize().into()
+}
+
+fn apply_aes_128_ctr(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<(), WalletError> {
+    let mut cipher = Aes128Ctr::new_from_slices(key, iv)
+        .map_err(|_| WalletError::DeserializationError("keystore iv must be 16 bytes".into()))?;
+    cipher.apply_keystream(data);
+    Ok(())
+}
+
+impl KdfParams {
+    fn derive(&self, password: &[u8]) -> Result<Zeroizing<Vec<u8>>, WalletError> {
+        let kdf_err = |e: anyhow::Error| WalletError::KeyDerivationError(e.to_string());
+        match self {
+            KdfParams::Scrypt { dklen, n, r, p, salt } => {
+                if *dklen != DKLEN {
+                    return Err(WalletError::ValidationError(format!("unsupported scrypt dklen {}", dklen)));
+                }
+                if !n.is_power_of_two() || *n < 2 || *n > MAX_SCRYPT_N || *r > MAX_SCRYPT_R || *p > MAX_SCRYPT_P {
+                    return Err(WalletError::ValidationError(format!(
+                        "unsupported scrypt parameters n={} r={} p={}",
+                        n, r, p
+                    )));
+                }
+                let salt = decode_hex("salt", salt)?;
+                KeyDerivation::scrypt(*n, *r, *p).derive_key(password, &salt, DKLEN).map_err(kdf_err)
+            }
+            KdfParams::Pbkdf2 { c, dklen, prf, salt } => {
+                if *dklen != DKLEN {
+                    return Err(WalletError::ValidationError(format!("unsupported pbkdf2 dklen {}", dklen)));
+                }
+                if prf != "hmac-sha256" {
+                    return Err(WalletError::ValidationError(format!("unsupported pbkdf2 prf {}", prf)));
+                }
+                if *c == 0 || *c > MAX_PBKDF2_ROUNDS {
+                    return Err(WalletError::ValidationError(format!("unsupported pbkdf2 rounds {}", c)));
+                }
+                let salt = decode_hex("salt", salt)?;
+                KeyDerivation::pbkdf2(*c).derive_key(password, &salt, DKLEN).map_err(kdf_err)
+            }
+        }
+    }
+}
+
+impl KeystoreV3 {
+    pub fn from_json(json: &str) -> Result<Self, WalletError> {
+        let keystore: Self = serde_json::from_str(json)
+            .map_err(|e| WalletError::DeserializationError(format!("invalid keystore: {}", e)))?;
+        if keystore.version != 3 {
+            return Err(WalletError::ValidationError(format!(
+                "unsupported keystore version {}",
+                keystore.version
+            )));
+        }
+        let expected_kdf = match keystore.crypto.kdfparams {
+            KdfParams::Scrypt { .. } => "scrypt",
+            KdfParams::Pbkdf2 { .. } => "pbkdf2",
+        };
+        if keystore.crypto.kdf != expected_kdf {
+            return Err(WalletError::ValidationError(format!(
+                "kdf '{}' does not match its kdfparams",
+                keystore.crypto.kdf
+            )));
+        }
+        if keystore.crypto.cipher != CIPHER {
+            return Err(WalletError::ValidationError(format!(
+                "unsupported keystore cipher {}",
+                keystore.crypto.cipher
+            )));
+        }
+        Ok(keystore)
+    }
+
+    pub fn to_json(&self) -> Result<String, WalletError> {
+        serde_json::to_string(self).map_err(|e| WalletError::SerializationError(e.to_string()))
+    }
+
+    /// Verifies the MAC and returns the decrypted private key.
+    ///
+    /// A MAC mismatch (wrong password or tampered file) is reported as
+    /// `WalletError::DecryptionError`.
+    pub fn decrypt(&self, password: &str) -> Result<Zeroizing<Vec<u8>>, WalletError> {
+        let ciphertext = decode_hex("ciphertext", &self.crypto.ciphertext)?;
+        let mac = decode_hex("mac", &self.crypto.mac)?;
+        let iv = decode_hex("iv", &self.crypto.cipherparams.iv)?;
+
+        let derived_key = self.crypto.kdfparams.derive(password.as_bytes())?;
+        let computed = keccak_mac(&derived_key, &ciphertext);
+        if !bool::from(computed[..].ct_eq(&mac[..])) {
+            return Err(WalletError::DecryptionError(
+                "keystore MAC mismatch: wrong password or corrupted keystore".into(),
+            ));
+        }
+
+        let mut plaintext = Zeroizing::new(ciphertext);
+        apply_aes_128_ctr(&derived_key[..16], &iv, &mut plaintext)?;
+        Ok(plaintext)
+    }
+
+    /// Encrypts `private_key` with scrypt (`n`, r=8, p=1), a random salt/iv
+    /// and a random UUID. `address` is the hex address the key controls.
+    pub fn encrypt(private_key: &[u8], password: &str, address: &str, n: u32) -> Result<Self, WalletError> {
+        let mut salt = [0u8; 32];
+        let mut iv = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut iv);
+
+        let kdfparams = KdfParams::Scrypt { dklen: DKLEN, n, r: SCRYPT_R, p: SCRYPT_P, salt: hex::encode(salt) };
+        let derived_key = kdfparams.derive(password.as_bytes())?;
+
+        let mut ciphertext = private_key.to_vec();
+        apply_aes_128_ctr(&derived_key[..16], &iv, &mut ciphertext)?;
+        let mac = keccak_mac(&derived_key, &ciphertext);
+
+        Ok(Self {
+            address: Some(address.trim_start_matches("0x").to_lowercase()),
+            crypto: KeystoreCrypto {
+                cipher: CIPHER.to_string(),
+                cipherparams: CipherParams { iv: hex::encode(iv) },
+                ciphertext: hex::encode(ciphertext),
+                kdf: "scrypt".to_string(),
+                kdfparams,
+                mac: hex::encode(mac),
+            },
+            id: uuid::Uuid::new_v4().to_string(),
+            version: 3,
+        })
+    }
+
+    /// Whether the embedded address (if any) matches `address`.
+    pub fn address_matches(&self, address: &str) -> bool {
+        match &self.address {
+            Some(own) => own.trim_start_matches("0x").eq_ignore_ascii_case(address.trim_start_matches("0x")),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Web3 Secret Storage pbkdf2 test vector (password "testpassword")
+    const PBKDF2_VECTOR: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": {"iv": "6087dab2f9fdbbfaddc31a909735c1e6"},
+            "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "kdf": "pbkdf2",
+            "kdfparams": {"c": 262144, "dklen": 32, "prf": "hmac-sha256", "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"},
+            "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+    const VECTOR_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+    #[test]
+    fn test_decrypt_pbkdf2_vector() {
+        let ks = KeystoreV3::from_json(PBKDF2_VECTOR).unwrap();
+        let key = ks.decrypt("testpassword").unwrap();
+        assert_eq!(hex::encode(&*key), VECTOR_KEY);
+    }
+
+    #[test]
+    fn test_wrong_password_fails_mac() {
+        let ks = KeystoreV3::from_json(PBKDF2_VECTOR).unwrap();
+        assert!(matches!(ks.decrypt("wrongpassword"), Err(WalletError::DecryptionError(_))));
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let key = hex::decode(VECTOR_KEY).unwrap();
+        let ks = KeystoreV3::encrypt(&key, "round-trip", "0x008AEEDA4D805471DF9B2A5B0F38A0C3BCBA786B", 1024).unwrap();
+        assert_eq!(ks.address.as_deref(), Some("008aeeda4d805471df9b2a5b0f38a0c3bcba786b"));
+
+        let parsed = KeystoreV3::from_json(&ks.to_json().unwrap()).unwrap();
+        assert_eq!(hex::encode(&*parsed.decrypt("round-trip").unwrap()), VECTOR_KEY);
+    }
+
+    #[test]
+    fn test_rejects_oversized_kdf_params() {
+        let json = PBKDF2_VECTOR.replace("\"c\": 262144", "\"c\": 4000000000");
+        let ks = KeystoreV3::from_json(&json).unwrap();
+        assert!(matches!(ks.decrypt("testpassword"), Err(WalletError::ValidationError(_))));
+    }
+}
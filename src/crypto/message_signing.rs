@@ -0,0 +1,269 @@
+//! Off-chain message signatures
+//!
+//! Two schemes, matching what wallets and exchanges verify:
+//!
+//! - **EIP-191** (`personal_sign`): keccak256 of
+//!   `"\x19Ethereum Signed Message:\n" || len || message`, 65-byte `r || s || v`
+//!   signature as 0x-hex.
+//! - **Bitcoin signed message** (BIP-137 legacy form): double SHA-256 of
+//!   `varint(24) || "Bitcoin Signed Message:\n" || varint(len) || message`,
+//!   65-byte `header || r || s` signature as base64, where
+//!   `header = 27 + recovery_id (+ 4 for a compressed key)`.
+//!
+//! Verification recovers the signing key and returns the address it
+//! controls; callers compare that with the claimed address.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Signature};
+use ethers::utils::{hash_message, to_checksum};
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+use crate::core::errors::WalletError;
+
+const BITCOIN_MAGIC: &[u8] = b"Bitcoin Signed Message:\n";
+
+/// Signing scheme of a message signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageScheme {
+    Eip191,
+    Bip137,
+}
+
+impl MessageScheme {
+    /// EIP-191 on every EVM network, the Bitcoin scheme on `btc`
+    pub fn for_network(network: &str) -> Self {
+        match network {
+            "btc" | "bitcoin" => MessageScheme::Bip137,
+            _ => MessageScheme::Eip191,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MessageScheme::Eip191 => "eip191",
+            MessageScheme::Bip137 => "bip137",
+        }
+    }
+}
+
+impl FromStr for MessageScheme {
+    type Err = WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "eip191" => Ok(MessageScheme::Eip191),
+            "bip137" => Ok(MessageScheme::Bip137),
+            other => Err(WalletError::ValidationError(format!("Unknown signature scheme: {}", other))),
+        }
+    }
+}
+
+/// An unlocked key that signs off-chain messages under one scheme
+pub struct MessageSigner {
+    scheme: MessageScheme,
+    address: String,
+    key: SignerKey,
+}
+
+enum SignerKey {
+    Evm(LocalWallet),
+    Bitcoin(Zeroizing<Vec<u8>>),
+}
+
+impl std::fmt::Debug for MessageSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageSigner")
+            .field("scheme", &self.scheme)
+            .field("address", &self.address)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MessageSigner {
+    /// `secret_key` is a 32-byte secp256k1 scalar
+    pub fn new(scheme: MessageScheme, secret_key: &[u8]) -> Result<Self, WalletError> {
+        let (address, key) = match scheme {
+            MessageScheme::Eip191 => {
+                let key = ethers::core::k256::ecdsa::SigningKey::from_slice(secret_key)
+                    .map_err(|e| WalletError::CryptoError(format!("Invalid secret key: {}", e)))?;
+                let signer = LocalWallet::from(key);
+                (to_checksum(&signer.address(), None), SignerKey::Evm(signer))
+            }
+            MessageScheme::Bip137 => {
+                (bitcoin_p2pkh_address(secret_key)?, SignerKey::Bitcoin(Zeroizing::new(secret_key.to_vec())))
+            }
+        };
+        Ok(Self { scheme, address, key })
+    }
+
+    pub fn scheme(&self) -> MessageScheme {
+        self.scheme
+    }
+
+    /// EIP-55 address, or the compressed-key P2PKH address on Bitcoin
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// 0x-hex (EIP-191) or base64 (Bitcoin) signature of `message`
+    pub fn sign(&self, message: &[u8]) -> Result<String, WalletError> {
+        match &self.key {
+            SignerKey::Evm(signer) => eip191_sign(signer, message),
+            SignerKey::Bitcoin(key) => bitcoin_sign(key, message),
+        }
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+fn bitcoin_p2pkh_address(secret_key: &[u8]) -> Result<String, WalletError> {
+    let secret_key = SecretKey::from_slice(secret_key)
+        .map_err(|e| WalletError::CryptoError(format!("Invalid secret key: {}", e)))?;
+    let pubkey = PublicKey::from_secret_key(&Secp256k1::signing_only(), &secret_key);
+    p2pkh_address(&pubkey.serialize())
+}
+
+#[cfg(not(feature = "bitcoin"))]
+fn bitcoin_p2pkh_address(_secret_key: &[u8]) -> Result<String, WalletError> {
+    Err(WalletError::ValidationError("Bitcoin feature not enabled".to_string()))
+}
+
+#[cfg(feature = "bitcoin")]
+fn p2pkh_address(pubkey: &[u8]) -> Result<String, WalletError> {
+    let pubkey = bitcoin::PublicKey::from_slice(pubkey).map_err(|e| WalletError::CryptoError(e.to_string()))?;
+    Ok(bitcoin::Address::p2pkh(&pubkey, bitcoin::Network::Bitcoin).to_string())
+}
+
+/// EIP-191 signature of `message`, 0x-hex
+pub fn eip191_sign(signer: &LocalWallet, message: &[u8]) -> Result<String, WalletError> {
+    let signature = signer
+        .sign_hash(hash_message(message))
+        .map_err(|e| WalletError::CryptoError(format!("Failed to sign message: {}", e)))?;
+    Ok(format!("0x{}", hex::encode(signature.to_vec())))
+}
+
+/// Address whose key produced the EIP-191 `signature` over `message`
+pub fn eip191_recover(message: &[u8], signature: &str) -> Result<Address, WalletError> {
+    let bytes = hex::decode(signature.strip_prefix("0x").unwrap_or(signature))
+        .map_err(|_| WalletError::ValidationError("Signature must be hex".to_string()))?;
+    let signature = Signature::try_from(bytes.as_slice())
+        .map_err(|_| WalletError::ValidationError("Signature must be 65 bytes".to_string()))?;
+    signature
+        .recover(message.to_vec())
+        .map_err(|_| WalletError::ValidationError("Signature does not recover to a key".to_string()))
+}
+
+/// Bitcoin compact-size integer
+fn write_varint(out: &mut Vec<u8>, n: usize) {
+    match n {
+        0..=0xfc => out.push(n as u8),
+        0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            out.push(0xfe);
+            out.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        _ => {
+            out.push(0xff);
+            out.extend_from_slice(&(n as u64).to_le_bytes());
+        }
+    }
+}
+
+/// Digest signed by the Bitcoin message scheme
+pub fn bitcoin_message_hash(message: &[u8]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(BITCOIN_MAGIC.len() + message.len() + 10);
+    write_varint(&mut data, BITCOIN_MAGIC.len());
+    data.extend_from_slice(BITCOIN_MAGIC);
+    write_varint(&mut data, message.len());
+    data.extend_from_slice(message);
+    Sha256::digest(Sha256::digest(&data)).into()
+}
+
+/// Bitcoin message signature by `secret_key` (compressed key), base64
+pub fn bitcoin_sign(secret_key: &[u8], message: &[u8]) -> Result<String, WalletError> {
+    let secret_key = SecretKey::from_slice(secret_key)
+        .map_err(|e| WalletError::CryptoError(format!("Invalid secret key: {}", e)))?;
+    let digest = Message::from_slice(&bitcoin_message_hash(message))
+        .map_err(|e| WalletError::CryptoError(e.to_string()))?;
+    let (recovery_id, compact) = Secp256k1::new().sign_ecdsa_recoverable(&digest, &secret_key).serialize_compact();
+    let mut out = Vec::with_capacity(65);
+    out.push(27 + recovery_id.to_i32() as u8 + 4);
+    out.extend_from_slice(&compact);
+    Ok(BASE64.encode(out))
+}
+
+/// Public key that produced the Bitcoin message `signature`, serialized the
+/// way the header says it was hashed into the address (33 or 65 bytes).
+pub fn bitcoin_recover_pubkey(message: &[u8], signature: &str) -> Result<Vec<u8>, WalletError> {
+    let bytes = BASE64
+        .decode(signature.trim())
+        .map_err(|_| WalletError::ValidationError("Signature must be base64".to_string()))?;
+    if bytes.len() != 65 {
+        return Err(WalletError::ValidationError("Signature must be 65 bytes".to_string()));
+    }
+    // 27-30 未压缩、31-34 压缩 P2PKH；segwit 头（35-42）不支持
+    let header = bytes[0];
+    if !(27..=34).contains(&header) {
+        return Err(WalletError::ValidationError("Unsupported signature header".to_string()));
+    }
+    let compressed = header >= 31;
+    let recovery_id = RecoveryId::from_i32(((header - 27) & 3) as i32)
+        .map_err(|_| WalletError::ValidationError("Invalid recovery id".to_string()))?;
+    let signature = RecoverableSignature::from_compact(&bytes[1..], recovery_id)
+        .map_err(|_| WalletError::ValidationError("Malformed signature".to_string()))?;
+    let digest = Message::from_slice(&bitcoin_message_hash(message))
+        .map_err(|e| WalletError::CryptoError(e.to_string()))?;
+    let key: PublicKey = Secp256k1::verification_only()
+        .recover_ecdsa(&digest, &signature)
+        .map_err(|_| WalletError::ValidationError("Signature does not recover to a key".to_string()))?;
+    Ok(if compressed { key.serialize().to_vec() } else { key.serialize_uncompressed().to_vec() })
+}
+
+/// Mainnet P2PKH address whose key produced the Bitcoin message `signature`
+#[cfg(feature = "bitcoin")]
+pub fn bitcoin_recover_address(message: &[u8], signature: &str) -> Result<String, WalletError> {
+    p2pkh_address(&bitcoin_recover_pubkey(message, signature)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint_prefix() {
+        let mut out = Vec::new();
+        write_varint(&mut out, 24);
+        write_varint(&mut out, 253);
+        write_varint(&mut out, 70_000);
+        assert_eq!(out, vec![24, 0xfd, 253, 0, 0xfe, 0x70, 0x11, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn test_eip191_round_trip() {
+        let signer: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318".parse().unwrap();
+        let signature = eip191_sign(&signer, b"prove it").unwrap();
+        assert_eq!(eip191_recover(b"prove it", &signature).unwrap(), signer.address());
+        assert_ne!(eip191_recover(b"prove it!", &signature).unwrap(), signer.address());
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn test_bitcoin_sign_recovers_compressed_p2pkh() {
+        // 私钥 1 的压缩公钥对应的 P2PKH address
+        let mut key = [0u8; 32];
+        key[31] = 1;
+        let signer = MessageSigner::new(MessageScheme::Bip137, &key).unwrap();
+        assert_eq!(signer.address(), "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH");
+        let signature = signer.sign(b"hello").unwrap();
+        assert_eq!(bitcoin_recover_address(b"hello", &signature).unwrap(), "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH");
+        assert_ne!(bitcoin_recover_address(b"hell0", &signature).unwrap(), "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH");
+    }
+}
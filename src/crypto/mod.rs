@@ -1,11 +1,15 @@
 pub mod encryption_consistency;
 pub mod hsm;
 pub mod kdf;
+pub mod keystore_envelope;
+pub mod keystore_v3;
+pub mod message_signing;
 pub mod multisig;
 pub mod quantum;
 pub mod secure_derivation;  // 🔐 Secure key derivation
 pub mod shamir;
 pub mod signature_utils;
+pub mod wallet_keystore;
 
 pub use self::encryption_consistency::{
     get_global_statistics, init_global_validator, validate_global_consistency, EncryptionAlgorithm,
@@ -17,6 +17,7 @@ pub mod policy;
 pub use config::{MultiSigConfig, AmountPrecision};
 pub use transaction::{MultiSigTransaction, PendingMultiSigTransaction};
 pub use signing::MultiSignature;
+pub use policy::{ThresholdTier, TieredThresholdPolicy};
 
 // 向后兼容：保留旧的导出路径
 #[deprecated(since = "0.3.0", note = "Use crypto::multisig::signing::MultiSignature")]
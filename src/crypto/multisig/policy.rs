@@ -3,6 +3,7 @@
 //! 提供阈值策略和sign权限管理
 
 use secp256k1::PublicKey;
+use serde::{Deserialize, Serialize};
 
 /// 阈值策略
 #[derive(Debug, Clone)]
@@ -42,6 +43,94 @@ impl ThresholdPolicy {
     }
 }
 
+/// 金额分档中的一档：金额 ≤ `max_amount`（最小单位）时需要 `required` 个sign
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdTier {
+    /// 最小单位（wei / satoshi），JSON 中为十进制字符串
+    #[serde(with = "u128_string")]
+    pub max_amount: u128,
+    pub required: u8,
+}
+
+/// 按金额分档的阈值策略
+///
+/// 档位按 `max_amount` 严格递增排列，`required` 随金额单调不减且不超过
+/// sign者总数。超过最高档的金额没有对应阈值，不能发起。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TieredThresholdPolicy {
+    tiers: Vec<ThresholdTier>,
+    signer_count: u8,
+}
+
+impl TieredThresholdPolicy {
+    pub fn new(tiers: Vec<ThresholdTier>, signer_count: u8) -> Result<Self, anyhow::Error> {
+        if tiers.is_empty() {
+            return Err(anyhow::anyhow!("At least one tier is required"));
+        }
+        for (i, tier) in tiers.iter().enumerate() {
+            if tier.required == 0 {
+                return Err(anyhow::anyhow!("Tier {}: required signatures must be at least 1", i));
+            }
+            if tier.required > signer_count {
+                return Err(anyhow::anyhow!(
+                    "Tier {}: required signatures ({}) exceed signer count ({})",
+                    i,
+                    tier.required,
+                    signer_count
+                ));
+            }
+        }
+        for (i, pair) in tiers.windows(2).enumerate() {
+            if pair[1].max_amount <= pair[0].max_amount {
+                return Err(anyhow::anyhow!("Tier {}: max_amount must be greater than the previous tier", i + 1));
+            }
+            if pair[1].required < pair[0].required {
+                return Err(anyhow::anyhow!(
+                    "Tier {}: required signatures must not decrease as the amount grows",
+                    i + 1
+                ));
+            }
+        }
+        Ok(Self { tiers, signer_count })
+    }
+
+    pub fn tiers(&self) -> &[ThresholdTier] {
+        &self.tiers
+    }
+
+    pub fn signer_count(&self) -> u8 {
+        self.signer_count
+    }
+
+    /// 第一档 `max_amount >= amount` 的阈值（边界金额归入该档）
+    pub fn required_for(&self, amount: u128) -> Result<u8, anyhow::Error> {
+        self.tiers
+            .iter()
+            .find(|tier| amount <= tier.max_amount)
+            .map(|tier| tier.required)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Amount {} exceeds the highest policy tier ({})",
+                    amount,
+                    self.tiers.last().map(|t| t.max_amount).unwrap_or_default()
+                )
+            })
+    }
+}
+
+mod u128_string {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(|_| D::Error::custom(format!("invalid amount: {}", raw)))
+    }
+}
+
 /// sign权限管理
 pub struct SignerPermissions {
     /// 白名单sign者
@@ -116,6 +205,43 @@ mod tests {
         assert!(ThresholdPolicy::m_of_n(4, 3).is_err());
     }
 
+    fn tier(max_amount: u128, required: u8) -> ThresholdTier {
+        ThresholdTier { max_amount, required }
+    }
+
+    #[test]
+    fn test_tier_selection_at_boundaries() {
+        let policy =
+            TieredThresholdPolicy::new(vec![tier(1_000, 1), tier(10_000, 2), tier(100_000, 3)], 3)
+                .unwrap();
+        assert_eq!(policy.required_for(0).unwrap(), 1);
+        assert_eq!(policy.required_for(1_000).unwrap(), 1);
+        assert_eq!(policy.required_for(1_001).unwrap(), 2);
+        assert_eq!(policy.required_for(10_000).unwrap(), 2);
+        assert_eq!(policy.required_for(100_000).unwrap(), 3);
+        assert!(policy.required_for(100_001).is_err());
+    }
+
+    #[test]
+    fn test_invalid_tiered_policy() {
+        // 金额更大但阈值更低
+        assert!(TieredThresholdPolicy::new(vec![tier(1_000, 2), tier(10_000, 1)], 3).is_err());
+        // 档位未按金额递增
+        assert!(TieredThresholdPolicy::new(vec![tier(10_000, 1), tier(10_000, 2)], 3).is_err());
+        // 超过sign者总数
+        assert!(TieredThresholdPolicy::new(vec![tier(1_000, 1), tier(10_000, 4)], 3).is_err());
+        assert!(TieredThresholdPolicy::new(vec![tier(1_000, 0)], 3).is_err());
+        assert!(TieredThresholdPolicy::new(vec![], 3).is_err());
+    }
+
+    #[test]
+    fn test_tier_amount_serializes_as_string() {
+        let json = serde_json::to_value(tier(u128::MAX, 2)).unwrap();
+        assert_eq!(json["max_amount"], u128::MAX.to_string());
+        let back: ThresholdTier = serde_json::from_value(json).unwrap();
+        assert_eq!(back.max_amount, u128::MAX);
+    }
+
     #[test]
     fn test_completion_rate() {
         let policy = ThresholdPolicy::m_of_n(3, 5).unwrap();
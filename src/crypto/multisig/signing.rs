@@ -4,8 +4,10 @@
 
 use super::{
     config::AmountPrecision,
+    policy::TieredThresholdPolicy,
     transaction::MultiSigTransaction,
 };
+use crate::security::memory_protection::TaintedBytes;
 use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1};
 use std::collections::HashMap;
 use tracing::info;
@@ -64,6 +66,40 @@ impl MultiSignature {
         Ok(tx_id)
     }
 
+    /// 按分档策略创建多签transaction
+    ///
+    /// `amount` 为最小单位。所需sign数在此时由匹配的档位确定并连同策略快照
+    /// 一起冻结在transaction上，执行时只看冻结值。
+    pub fn create_tiered_transaction(
+        &mut self,
+        wallet: &str,
+        to_address: &str,
+        amount: u128,
+        network: &str,
+        allowed_signers: Vec<PublicKey>,
+        policy: &TieredThresholdPolicy,
+    ) -> Result<String> {
+        let required = policy.required_for(amount)?;
+        let tx_id = format!("multisig_{}", uuid::Uuid::new_v4());
+
+        let mut tx = MultiSigTransaction::new(
+            tx_id.clone(),
+            to_address.to_string(),
+            amount.to_string(),
+            network.to_string(),
+            required,
+        );
+        tx.amount_precision = AmountPrecision::Minimal;
+        tx.allowed_signers = Some(allowed_signers);
+        tx.wallet = Some(wallet.to_string());
+        tx.policy = Some(policy.clone());
+
+        info!("📝 Created tiered multi-sig transaction: {} ({} signatures required)", tx_id, required);
+        self.pending_transactions.insert(tx_id.clone(), tx);
+
+        Ok(tx_id)
+    }
+
     /// 为transactionsign
     ///
     /// # Arguments
@@ -138,6 +174,26 @@ impl MultiSignature {
         Ok(is_complete)
     }
 
+    /// 用本方持有的密钥sign并登记（服务端作为共同sign者时）
+    ///
+    /// 密钥只借用给 secp256k1，sign后立即擦除派生出的 `SecretKey`。
+    pub fn approve_with_key(&mut self, tx_id: &str, key: &TaintedBytes) -> Result<bool> {
+        let transaction = self
+            .pending_transactions
+            .get(tx_id)
+            .ok_or_else(|| anyhow::anyhow!("Transaction not found: {}", tx_id))?;
+        let message = Self::build_canonical_message(transaction)?;
+
+        let secp = Secp256k1::new();
+        let mut secret = secp256k1::SecretKey::from_slice(key)
+            .map_err(|e| anyhow::anyhow!("Invalid signer key: {}", e))?;
+        let pubkey = PublicKey::from_secret_key(&secp, &secret);
+        let signature = secp.sign_ecdsa(&message, &secret);
+        secret.non_secure_erase();
+
+        self.sign_transaction(tx_id, &pubkey, &signature)
+    }
+
     /// 构建规范消息用于sign
     fn build_canonical_message(tx: &MultiSigTransaction) -> Result<Message> {
         use sha2::{Digest, Sha256};
@@ -241,5 +297,97 @@ mod tests {
         assert!(tx_id.starts_with("multisig_"));
         assert!(manager.get_transaction(&tx_id).is_some());
     }
+
+    fn signer(seed: u8) -> (secp256k1::SecretKey, PublicKey) {
+        let secp = Secp256k1::new();
+        let secret = secp256k1::SecretKey::from_slice(&[seed; 32]).unwrap();
+        (secret, PublicKey::from_secret_key(&secp, &secret))
+    }
+
+    fn tiered_policy(tiers: &[(u128, u8)]) -> TieredThresholdPolicy {
+        let tiers = tiers
+            .iter()
+            .map(|&(max_amount, required)| super::super::policy::ThresholdTier { max_amount, required })
+            .collect();
+        TieredThresholdPolicy::new(tiers, 3).unwrap()
+    }
+
+    fn sign(manager: &mut MultiSignature, tx_id: &str, secret: &secp256k1::SecretKey) -> Result<bool> {
+        let secp = Secp256k1::new();
+        let pubkey = PublicKey::from_secret_key(&secp, secret);
+        let message = MultiSignature::build_canonical_message(manager.get_transaction(tx_id).unwrap())?;
+        let signature = secp.sign_ecdsa(&message, secret);
+        manager.sign_transaction(tx_id, &pubkey, &signature)
+    }
+
+    #[test]
+    fn test_tiered_threshold_frozen_at_creation() {
+        let signers: Vec<_> = (1..=3).map(signer).collect();
+        let pubkeys: Vec<_> = signers.iter().map(|(_, pk)| *pk).collect();
+        let mut manager = MultiSignature::new(2);
+
+        let policy = tiered_policy(&[(1_000, 1), (10_000, 2), (100_000, 3)]);
+        let tx_id = manager
+            .create_tiered_transaction("vault", "0x1234", 10_000, "eth", pubkeys.clone(), &policy)
+            .unwrap();
+        manager.set_nonce_and_chain_id(&tx_id, 7, 1).unwrap();
+
+        // 之后放宽策略：已创建的transaction仍需 2 个sign
+        let relaxed = tiered_policy(&[(100_000, 1)]);
+        let later = manager
+            .create_tiered_transaction("vault", "0x1234", 10_000, "eth", pubkeys, &relaxed)
+            .unwrap();
+        assert_eq!(manager.get_transaction(&later).unwrap().threshold, 1);
+
+        let tx = manager.get_transaction(&tx_id).unwrap();
+        assert_eq!(tx.threshold, 2);
+        assert_eq!(tx.policy.as_ref(), Some(&policy));
+
+        // 差一个sign时不能执行
+        assert!(!sign(&mut manager, &tx_id, &signers[0].0).unwrap());
+        let err = manager.execute_transaction(&tx_id).unwrap_err();
+        assert!(err.to_string().contains("Not enough signatures: 1/2"), "{}", err);
+
+        assert!(sign(&mut manager, &tx_id, &signers[1].0).unwrap());
+        manager.execute_transaction(&tx_id).unwrap();
+    }
+
+    #[test]
+    fn test_approve_with_key_does_not_leak_key() {
+        use crate::security::memory_protection::assert_no_secret_leaks;
+
+        let key = TaintedBytes::new(crate::security::SecretVec::new(vec![7u8; 32]), "cosigner key");
+        let (_, cosigner) = signer(7);
+        let (other, other_pubkey) = signer(8);
+        let mut manager = MultiSignature::new(2);
+        let policy = tiered_policy(&[(1_000, 2)]);
+        let tx_id = manager
+            .create_tiered_transaction("vault", "0x1234", 500, "eth", vec![cosigner, other_pubkey], &policy)
+            .unwrap();
+        manager.set_nonce_and_chain_id(&tx_id, 1, 1).unwrap();
+
+        assert!(!manager.approve_with_key(&tx_id, &key).unwrap());
+        assert!(sign(&mut manager, &tx_id, &other).unwrap());
+        manager.execute_transaction(&tx_id).unwrap();
+        assert_no_secret_leaks();
+    }
+
+    #[test]
+    fn test_tiered_transaction_rejects_unknown_signer_and_oversized_amount() {
+        let (_, allowed) = signer(1);
+        let (outsider, _) = signer(9);
+        let mut manager = MultiSignature::new(2);
+        let policy = tiered_policy(&[(1_000, 1)]);
+
+        assert!(manager
+            .create_tiered_transaction("vault", "0x1234", 1_001, "eth", vec![allowed], &policy)
+            .is_err());
+
+        let tx_id = manager
+            .create_tiered_transaction("vault", "0x1234", 1_000, "eth", vec![allowed], &policy)
+            .unwrap();
+        manager.set_nonce_and_chain_id(&tx_id, 1, 1).unwrap();
+        assert!(sign(&mut manager, &tx_id, &outsider).is_err());
+    }
 }
 
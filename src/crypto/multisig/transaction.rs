@@ -6,6 +6,7 @@ use secp256k1::{ecdsa::Signature, PublicKey};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
 use super::config::AmountPrecision;
+use super::policy::TieredThresholdPolicy;
 
 /// 多签transaction
 #[derive(Debug, Clone)]
@@ -32,6 +33,10 @@ pub struct MultiSigTransaction {
     pub chain_id: Option<u64>,
     /// 金额精度
     pub amount_precision: AmountPrecision,
+    /// 发起方wallet（按分档策略创建时设置）
+    pub wallet: Option<String>,
+    /// 创建时的分档策略快照；`threshold` 由它得出，之后策略变更不影响本交易
+    pub policy: Option<TieredThresholdPolicy>,
 }
 
 impl MultiSigTransaction {
@@ -55,6 +60,8 @@ impl MultiSigTransaction {
             nonce: None,
             chain_id: None,
             amount_precision: AmountPrecision::Raw,
+            wallet: None,
+            policy: None,
         }
     }
 
@@ -0,0 +1,341 @@
+//! Versioned JSON keystore for moving a whole wallet between instances.
+//!
+//! Modelled on the Ethereum V3 keystore, but carries the wallet's own key
+//! (HD master key or imported key) together with the metadata needed to
+//! recreate it, and uses AES-256-GCM under an argon2id (or scrypt) key.
+//! The KDF produces 64 bytes: the first half is the cipher key, the second
+//! half keys an HMAC-SHA256 over the metadata, nonce and ciphertext. The MAC
+//! is checked before anything is decrypted, so a wrong password is reported
+//! as [`WalletError::InvalidPassword`] without touching the ciphertext.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use crate::core::errors::WalletError;
+use crate::core::wallet_info::WalletKeyKind;
+use crate::core::wallet_manager::derivation::DerivationPath;
+use crate::crypto::kdf::KeyDerivation;
+
+/// Current format version; readers refuse anything else.
+pub const WALLET_KEYSTORE_VERSION: u8 = 1;
+const CIPHER: &str = "aes-256-gcm";
+const MAC_DOMAIN: &[u8] = b"defi-hot-wallet/keystore/v1";
+const DKLEN: usize = 64;
+const SALT_LEN: usize = 32;
+
+/// argon2id cost used on export (OWASP baseline: 19 MiB, 2 passes).
+pub const DEFAULT_ARGON2_M_COST: u32 = 19_456;
+const ARGON2_T_COST: u32 = 2;
+const ARGON2_P_COST: u32 = 1;
+
+// Refuse KDF parameters that would tie up a worker for minutes or exhaust memory.
+const MAX_ARGON2_M_COST: u32 = 1 << 20;
+const MAX_ARGON2_T_COST: u32 = 16;
+const MAX_ARGON2_P_COST: u32 = 16;
+const MAX_SCRYPT_N: u32 = 1 << 20;
+const MAX_SCRYPT_R: u32 = 32;
+const MAX_SCRYPT_P: u32 = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletKeystore {
+    pub version: u8,
+    pub id: String,
+    pub wallet: WalletKeystoreMeta,
+    pub crypto: WalletKeystoreCrypto,
+}
+
+/// Wallet settings carried alongside the key; covered by the MAC.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletKeystoreMeta {
+    /// Name on the exporting instance; informational, the importer picks its own.
+    pub name: String,
+    pub key_kind: WalletKeyKind,
+    pub quantum_safe: bool,
+    pub multi_sig_threshold: u8,
+    pub networks: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_networks: Option<Vec<String>>,
+    /// Signing-key path below an HD master key; older keystores have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<DerivationPath>,
+    /// Ethereum address (0x...) of the key, checked again on import.
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletKeystoreCrypto {
+    pub cipher: String,
+    pub nonce: String,
+    pub ciphertext: String,
+    pub kdf: String,
+    pub kdfparams: WalletKdfParams,
+    pub mac: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WalletKdfParams {
+    Argon2id { dklen: usize, m_cost: u32, t_cost: u32, p_cost: u32, salt: String },
+    Scrypt { dklen: usize, n: u32, r: u32, p: u32, salt: String },
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>, WalletError> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| WalletError::DeserializationError(format!("keystore {}: {}", field, e)))
+}
+
+impl WalletKdfParams {
+    fn name(&self) -> &'static str {
+        match self {
+            WalletKdfParams::Argon2id { .. } => "argon2id",
+            WalletKdfParams::Scrypt { .. } => "scrypt",
+        }
+    }
+
+    fn derive(&self, password: &[u8]) -> Result<Zeroizing<Vec<u8>>, WalletError> {
+        let kdf_err = |e: anyhow::Error| WalletError::KeyDerivationError(e.to_string());
+        let (kdf, dklen, salt) = match self {
+            WalletKdfParams::Argon2id { dklen, m_cost, t_cost, p_cost, salt } => {
+                if *m_cost > MAX_ARGON2_M_COST
+                    || *t_cost == 0
+                    || *t_cost > MAX_ARGON2_T_COST
+                    || *p_cost > MAX_ARGON2_P_COST
+                {
+                    return Err(WalletError::ValidationError(format!(
+                        "unsupported argon2id parameters m={} t={} p={}",
+                        m_cost, t_cost, p_cost
+                    )));
+                }
+                (KeyDerivation::argon2id(*m_cost, *t_cost, *p_cost), dklen, salt)
+            }
+            WalletKdfParams::Scrypt { dklen, n, r, p, salt } => {
+                if !n.is_power_of_two() || *n < 2 || *n > MAX_SCRYPT_N || *r > MAX_SCRYPT_R || *p > MAX_SCRYPT_P {
+                    return Err(WalletError::ValidationError(format!(
+                        "unsupported scrypt parameters n={} r={} p={}",
+                        n, r, p
+                    )));
+                }
+                (KeyDerivation::scrypt(*n, *r, *p), dklen, salt)
+            }
+        };
+        if *dklen != DKLEN {
+            return Err(WalletError::ValidationError(format!("unsupported {} dklen {}", self.name(), dklen)));
+        }
+        kdf.derive_key(password, &decode_hex("salt", salt)?, DKLEN).map_err(kdf_err)
+    }
+}
+
+fn compute_mac(
+    mac_key: &[u8],
+    meta: &WalletKeystoreMeta,
+    nonce: &[u8],
+    ciphertext: &[u8],
+) -> Result<[u8; 32], WalletError> {
+    let meta = serde_json::to_vec(meta).map_err(|e| WalletError::SerializationError(e.to_string()))?;
+    let mut mac =
+        <Hmac<Sha256> as KeyInit>::new_from_slice(mac_key).map_err(|e| WalletError::CryptoError(e.to_string()))?;
+    mac.update(MAC_DOMAIN);
+    for part in [&meta[..], nonce, ciphertext] {
+        mac.update(&(part.len() as u64).to_be_bytes());
+        mac.update(part);
+    }
+    Ok(mac.finalize().into_bytes().into())
+}
+
+impl WalletKeystore {
+    pub fn from_json(json: &str) -> Result<Self, WalletError> {
+        let keystore: Self = serde_json::from_str(json)
+            .map_err(|e| WalletError::DeserializationError(format!("invalid wallet keystore: {}", e)))?;
+        if keystore.version != WALLET_KEYSTORE_VERSION {
+            return Err(WalletError::ValidationError(format!(
+                "unsupported wallet keystore version {}",
+                keystore.version
+            )));
+        }
+        if keystore.crypto.kdf != keystore.crypto.kdfparams.name() {
+            return Err(WalletError::ValidationError(format!(
+                "kdf '{}' does not match its kdfparams",
+                keystore.crypto.kdf
+            )));
+        }
+        if keystore.crypto.cipher != CIPHER {
+            return Err(WalletError::ValidationError(format!(
+                "unsupported keystore cipher {}",
+                keystore.crypto.cipher
+            )));
+        }
+        Ok(keystore)
+    }
+
+    pub fn to_json(&self) -> Result<String, WalletError> {
+        serde_json::to_string(self).map_err(|e| WalletError::SerializationError(e.to_string()))
+    }
+
+    /// Encrypts `key` with argon2id (`m_cost` KiB, t=2, p=1) and a random
+    /// salt, nonce and UUID.
+    pub fn encrypt(key: &[u8], password: &str, wallet: WalletKeystoreMeta, m_cost: u32) -> Result<Self, WalletError> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let kdfparams = WalletKdfParams::Argon2id {
+            dklen: DKLEN,
+            m_cost,
+            t_cost: ARGON2_T_COST,
+            p_cost: ARGON2_P_COST,
+            salt: hex::encode(salt),
+        };
+        let derived_key = kdfparams.derive(password.as_bytes())?;
+
+        let cipher = Aes256Gcm::new_from_slice(&derived_key[..32])
+            .map_err(|_| WalletError::CryptoError("Failed to create AES cipher".into()))?;
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), key)
+            .map_err(|_| WalletError::EncryptionError("Failed to encrypt wallet key".into()))?;
+        let mac = compute_mac(&derived_key[32..], &wallet, &nonce, &ciphertext)?;
+
+        Ok(Self {
+            version: WALLET_KEYSTORE_VERSION,
+            id: uuid::Uuid::new_v4().to_string(),
+            wallet,
+            crypto: WalletKeystoreCrypto {
+                cipher: CIPHER.to_string(),
+                nonce: hex::encode(nonce),
+                ciphertext: hex::encode(ciphertext),
+                kdf: kdfparams.name().to_string(),
+                kdfparams,
+                mac: hex::encode(mac),
+            },
+        })
+    }
+
+    /// Verifies the MAC and returns the decrypted wallet key.
+    ///
+    /// A MAC mismatch (wrong password or tampered file) is reported as
+    /// `WalletError::InvalidPassword`.
+    pub fn decrypt(&self, password: &str) -> Result<Zeroizing<Vec<u8>>, WalletError> {
+        let ciphertext = decode_hex("ciphertext", &self.crypto.ciphertext)?;
+        let nonce = decode_hex("nonce", &self.crypto.nonce)?;
+        let mac = decode_hex("mac", &self.crypto.mac)?;
+        if nonce.len() != 12 {
+            return Err(WalletError::DeserializationError("keystore nonce must be 12 bytes".into()));
+        }
+
+        let derived_key = self.crypto.kdfparams.derive(password.as_bytes())?;
+        let computed = compute_mac(&derived_key[32..], &self.wallet, &nonce, &ciphertext)?;
+        if !bool::from(computed[..].ct_eq(&mac[..])) {
+            return Err(WalletError::InvalidPassword(
+                "keystore MAC mismatch: wrong password or corrupted keystore".into(),
+            ));
+        }
+
+        let cipher = Aes256Gcm::new_from_slice(&derived_key[..32])
+            .map_err(|_| WalletError::CryptoError("Failed to create AES cipher".into()))?;
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let key = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| WalletError::DecryptionError("wallet keystore ciphertext is corrupted".into()))?;
+        Ok(Zeroizing::new(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [7u8; 32];
+
+    fn meta() -> WalletKeystoreMeta {
+        WalletKeystoreMeta {
+            name: "treasury".into(),
+            key_kind: WalletKeyKind::Hd,
+            quantum_safe: false,
+            multi_sig_threshold: 2,
+            networks: vec!["eth".into(), "polygon".into()],
+            allowed_networks: None,
+            derivation_path: None,
+            address: "0x0000000000000000000000000000000000000001".into(),
+        }
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let ks = WalletKeystore::encrypt(&KEY, "r0und-Trip", meta(), 1024).unwrap();
+        assert_eq!(ks.crypto.kdf, "argon2id");
+
+        let parsed = WalletKeystore::from_json(&ks.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.wallet, meta());
+        assert_eq!(&*parsed.decrypt("r0und-Trip").unwrap(), &KEY);
+    }
+
+    #[test]
+    fn test_wrong_password_is_invalid_password() {
+        let ks = WalletKeystore::encrypt(&KEY, "r0und-Trip", meta(), 1024).unwrap();
+        assert!(matches!(ks.decrypt("wrong-password"), Err(WalletError::InvalidPassword(_))));
+    }
+
+    #[test]
+    fn test_metadata_is_covered_by_the_mac() {
+        let mut ks = WalletKeystore::encrypt(&KEY, "r0und-Trip", meta(), 1024).unwrap();
+        ks.wallet.allowed_networks = Some(vec!["bsc".into()]);
+        assert!(matches!(ks.decrypt("r0und-Trip"), Err(WalletError::InvalidPassword(_))));
+
+        // a path swapped in would change every derived address
+        let mut ks = WalletKeystore::encrypt(&KEY, "r0und-Trip", meta(), 1024).unwrap();
+        ks.wallet.derivation_path = Some(DerivationPath::parse("m/44'/60'/0'/0/7").unwrap());
+        assert!(matches!(ks.decrypt("r0und-Trip"), Err(WalletError::InvalidPassword(_))));
+    }
+
+    #[test]
+    fn test_reads_scrypt_keystores() {
+        let wallet = meta();
+        let kdfparams = WalletKdfParams::Scrypt { dklen: DKLEN, n: 1024, r: 8, p: 1, salt: hex::encode([3u8; 32]) };
+        let derived_key = kdfparams.derive(b"scrypt-Pass1").unwrap();
+        let nonce = [9u8; 12];
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let ciphertext = Aes256Gcm::new_from_slice(&derived_key[..32])
+            .unwrap()
+            .encrypt(Nonce::from_slice(&nonce), &KEY[..])
+            .unwrap();
+        let mac = compute_mac(&derived_key[32..], &wallet, &nonce, &ciphertext).unwrap();
+        let json = serde_json::json!({
+            "version": 1,
+            "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+            "wallet": wallet,
+            "crypto": {
+                "cipher": "aes-256-gcm",
+                "nonce": hex::encode(nonce),
+                "ciphertext": hex::encode(ciphertext),
+                "kdf": "scrypt",
+                "kdfparams": kdfparams,
+                "mac": hex::encode(mac),
+            }
+        });
+
+        let ks = WalletKeystore::from_json(&json.to_string()).unwrap();
+        assert_eq!(&*ks.decrypt("scrypt-Pass1").unwrap(), &KEY);
+    }
+
+    #[test]
+    fn test_rejects_unknown_version_and_oversized_kdf_params() {
+        let ks = WalletKeystore::encrypt(&KEY, "r0und-Trip", meta(), 1024).unwrap();
+        let json = ks.to_json().unwrap();
+
+        let future = json.replacen("\"version\":1", "\"version\":2", 1);
+        assert!(matches!(WalletKeystore::from_json(&future), Err(WalletError::ValidationError(_))));
+
+        let greedy = json.replacen("\"m_cost\":1024", "\"m_cost\":4000000000", 1);
+        let ks = WalletKeystore::from_json(&greedy).unwrap();
+        assert!(matches!(ks.decrypt("r0und-Trip"), Err(WalletError::ValidationError(_))));
+    }
+}
@@ -0,0 +1,161 @@
+//! JSON-RPC client for an ERC-4337 bundler (`eth_*UserOperation*` methods).
+
+use ethers::types::{Address, H256, U256, U64};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::user_operation::UserOperation;
+use crate::relay::forwarder::de_u256;
+
+/// Why a bundler call failed
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BundlerError {
+    /// The bundler could not be reached or answered with something that is not JSON-RPC
+    #[error("bundler request failed: {0}")]
+    Transport(String),
+    /// JSON-RPC error object, e.g. `-32500` with `AA21 didn't pay prefund`
+    #[error("bundler error {code}: {message}")]
+    Rpc { code: i64, message: String },
+}
+
+impl BundlerError {
+    /// EntryPoint error code (`AA10` … `AA95`) in the message, if any
+    pub fn aa_code(&self) -> Option<String> {
+        match self {
+            BundlerError::Rpc { message, .. } => parse_aa_code(message),
+            BundlerError::Transport(_) => None,
+        }
+    }
+}
+
+/// Finds the first `AA` + two digits token, as EntryPoint's `FailedOp`
+/// reasons and bundler messages spell them.
+pub fn parse_aa_code(message: &str) -> Option<String> {
+    let bytes = message.as_bytes();
+    (0..bytes.len().saturating_sub(3)).find_map(|i| {
+        let word_start = i == 0 || !bytes[i - 1].is_ascii_alphanumeric();
+        let word_end = bytes.get(i + 4).is_none_or(|b| !b.is_ascii_alphanumeric());
+        (word_start
+            && word_end
+            && &bytes[i..i + 2] == b"AA"
+            && bytes[i + 2].is_ascii_digit()
+            && bytes[i + 3].is_ascii_digit())
+        .then(|| message[i..i + 4].to_string())
+    })
+}
+
+/// `eth_estimateUserOperationGas` result. Bundlers differ in hex vs decimal,
+/// both are accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationGasEstimate {
+    #[serde(deserialize_with = "de_u256")]
+    pub pre_verification_gas: U256,
+    #[serde(deserialize_with = "de_u256")]
+    pub verification_gas_limit: U256,
+    #[serde(deserialize_with = "de_u256")]
+    pub call_gas_limit: U256,
+}
+
+/// `eth_getUserOperationReceipt` result (the fields the wallet uses)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationReceipt {
+    pub user_op_hash: H256,
+    /// `false` when the account's call reverted; the op was still included
+    pub success: bool,
+    #[serde(deserialize_with = "de_u256")]
+    pub actual_gas_cost: U256,
+    #[serde(default)]
+    pub reason: Option<String>,
+    pub receipt: BundleReceipt,
+}
+
+/// The bundle transaction that included the op
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleReceipt {
+    pub transaction_hash: H256,
+    #[serde(default)]
+    pub block_number: Option<U64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BundlerClient {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl BundlerClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), http: reqwest::Client::new() }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, BundlerError> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = self
+            .http
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| BundlerError::Transport(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| BundlerError::Transport(format!("{}: invalid response: {}", method, e)))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(BundlerError::Rpc {
+                code: error.get("code").and_then(Value::as_i64).unwrap_or_default(),
+                message: error.get("message").and_then(Value::as_str).unwrap_or_default().to_string(),
+            });
+        }
+        serde_json::from_value(response.get("result").cloned().unwrap_or(Value::Null))
+            .map_err(|e| BundlerError::Transport(format!("{}: unexpected result: {}", method, e)))
+    }
+
+    pub async fn estimate_user_operation_gas(
+        &self,
+        op: &UserOperation,
+        entry_point: Address,
+    ) -> Result<UserOperationGasEstimate, BundlerError> {
+        self.request("eth_estimateUserOperationGas", json!([op, entry_point])).await
+    }
+
+    /// Returns the userOpHash the bundler computed
+    pub async fn send_user_operation(&self, op: &UserOperation, entry_point: Address) -> Result<H256, BundlerError> {
+        self.request("eth_sendUserOperation", json!([op, entry_point])).await
+    }
+
+    /// `None` while the op has not been included
+    pub async fn get_user_operation_receipt(
+        &self,
+        user_op_hash: H256,
+    ) -> Result<Option<UserOperationReceipt>, BundlerError> {
+        self.request("eth_getUserOperationReceipt", json!([user_op_hash])).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_aa_code() {
+        assert_eq!(parse_aa_code("AA21 didn't pay prefund").as_deref(), Some("AA21"));
+        assert_eq!(
+            parse_aa_code("UserOperation reverted during simulation with reason: AA23 reverted (or OOG)").as_deref(),
+            Some("AA23")
+        );
+        assert_eq!(parse_aa_code("FailedOp(0,\"AA25 invalid account nonce\")").as_deref(), Some("AA25"));
+        assert_eq!(parse_aa_code("AAA21 nope"), None);
+        assert_eq!(parse_aa_code("AA213"), None);
+        assert_eq!(parse_aa_code("gas too low"), None);
+        assert_eq!(parse_aa_code(""), None);
+    }
+}
@@ -0,0 +1,392 @@
+//! ERC-4337 account abstraction (EntryPoint v0.6, SimpleAccount)
+//!
+//! The wallet key acts as the owner of a smart account. A send wraps the
+//! call in the account's `execute(dest, value, data)`, asks the bundler for
+//! gas limits, signs the userOpHash with the owner key and submits the op.
+//! Until the account is deployed the op targets its counterfactual address
+//! and carries the factory's `createAccount(owner, salt)` as `initCode`.
+//!
+//! Accepted ops are recorded as `pending` and polled with
+//! `eth_getUserOperationReceipt`; inclusion stores the bundle transaction as
+//! a regular `TransactionRecord`.
+
+pub mod bundler;
+pub mod user_operation;
+
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionRequest, H256, U256};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::core::config::{AccountAbstractionConfig, BlockchainConfig, NetworkConfig};
+use crate::core::errors::WalletError;
+use crate::ops::incidents::spawn_named;
+use crate::storage::{
+    NewUserOperation, TransactionRecord, UserOperationRecord, WalletStorage, USER_OP_INCLUDED, USER_OP_PENDING,
+    USER_OP_REVERTED,
+};
+
+pub use bundler::{BundlerClient, BundlerError, UserOperationGasEstimate, UserOperationReceipt};
+pub use user_operation::{dummy_signature, encode_execute, init_code, UserOperation};
+
+/// Why a UserOperation was not sent. `code()` is part of the API contract.
+#[derive(Debug, thiserror::Error)]
+pub enum Erc4337Error {
+    #[error("Account abstraction is not configured for network {0}")]
+    NotConfigured(String),
+    #[error("Invalid user operation: {0}")]
+    InvalidRequest(String),
+    #[error("Gas estimation failed: {message}")]
+    GasEstimation { aa_code: Option<String>, message: String },
+    #[error("Bundler rejected the user operation: {message}")]
+    Rejected { aa_code: Option<String>, message: String },
+    #[error("Bundler unavailable: {0}")]
+    Bundler(String),
+    #[error("Node RPC failed: {0}")]
+    Rpc(String),
+    #[error(transparent)]
+    Signing(#[from] WalletError),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+impl Erc4337Error {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Erc4337Error::NotConfigured(_) => "AA_NOT_CONFIGURED",
+            Erc4337Error::InvalidRequest(_) => "INVALID_USER_OPERATION",
+            Erc4337Error::GasEstimation { aa_code, .. } => {
+                aa_code.as_deref().and_then(aa_error_code).unwrap_or("USER_OP_GAS_ESTIMATION_FAILED")
+            }
+            Erc4337Error::Rejected { aa_code, .. } => {
+                aa_code.as_deref().and_then(aa_error_code).unwrap_or("USER_OP_REJECTED")
+            }
+            Erc4337Error::Bundler(_) => "BUNDLER_UNAVAILABLE",
+            Erc4337Error::Rpc(_) => "RPC_ERROR",
+            Erc4337Error::Signing(_) => "SIGNING_FAILED",
+            Erc4337Error::Storage(_) => "DB_ERROR",
+        }
+    }
+
+    /// Transport failures stay transport failures; an error object from the
+    /// bundler becomes `wrap(aa_code, message)`.
+    fn from_bundler(e: BundlerError, wrap: fn(Option<String>, String) -> Self) -> Self {
+        match e {
+            BundlerError::Transport(message) => Erc4337Error::Bundler(message),
+            rpc @ BundlerError::Rpc { .. } => wrap(rpc.aa_code(), rpc.to_string()),
+        }
+    }
+}
+
+/// API error code for an EntryPoint `AAxx` code the caller can act on
+pub fn aa_error_code(aa_code: &str) -> Option<&'static str> {
+    Some(match aa_code {
+        "AA10" => "AA10_SENDER_ALREADY_CONSTRUCTED",
+        "AA13" => "AA13_INIT_CODE_FAILED",
+        "AA14" => "AA14_INIT_CODE_WRONG_SENDER",
+        "AA15" => "AA15_INIT_CODE_NO_DEPLOYMENT",
+        "AA20" => "AA20_ACCOUNT_NOT_DEPLOYED",
+        "AA21" => "AA21_PREFUND_NOT_PAID",
+        "AA22" => "AA22_EXPIRED_OR_NOT_DUE",
+        "AA23" => "AA23_ACCOUNT_REVERTED",
+        "AA24" => "AA24_SIGNATURE_ERROR",
+        "AA25" => "AA25_INVALID_NONCE",
+        "AA30" => "AA30_PAYMASTER_NOT_DEPLOYED",
+        "AA31" => "AA31_PAYMASTER_DEPOSIT_TOO_LOW",
+        "AA32" => "AA32_PAYMASTER_EXPIRED_OR_NOT_DUE",
+        "AA33" => "AA33_PAYMASTER_REVERTED",
+        "AA34" => "AA34_PAYMASTER_SIGNATURE_ERROR",
+        "AA40" => "AA40_VERIFICATION_GAS_EXCEEDED",
+        "AA41" => "AA41_VERIFICATION_GAS_TOO_LOW",
+        "AA51" => "AA51_PREFUND_BELOW_ACTUAL_GAS",
+        _ => return None,
+    })
+}
+
+/// What to execute from the owner's smart account
+#[derive(Debug, Clone)]
+pub struct AaSendRequest<'a> {
+    pub wallet_name: &'a str,
+    pub network: &'a str,
+    pub to: Address,
+    /// Wei
+    pub value: U256,
+    pub data: Bytes,
+    /// Passed through untouched; empty when the account pays its own gas
+    pub paymaster_and_data: Bytes,
+}
+
+/// Result of a successful submission
+#[derive(Debug, Clone, Serialize)]
+pub struct UserOpSubmission {
+    pub user_op_hash: String,
+    /// Smart account address
+    pub sender: String,
+    pub nonce: String,
+    /// `false` when this op deploys the account through `initCode`
+    pub account_deployed: bool,
+    pub network: String,
+    pub status: String,
+}
+
+/// The owner's account on one network
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmartAccount {
+    pub address: Address,
+    pub deployed: bool,
+}
+
+struct AaNetwork<'a> {
+    config: &'a NetworkConfig,
+    bundler: BundlerClient,
+}
+
+pub struct AccountAbstraction {
+    config: AccountAbstractionConfig,
+    entry_point: Address,
+    factory: Address,
+    networks: HashMap<String, NetworkConfig>,
+    storage: Arc<WalletStorage>,
+}
+
+fn hex_address(address: Address) -> String {
+    format!("{:?}", address)
+}
+
+fn first_word(data: &[u8]) -> Result<[u8; 32], Erc4337Error> {
+    data.get(..32)
+        .and_then(|word| word.try_into().ok())
+        .ok_or_else(|| Erc4337Error::Rpc(format!("eth_call returned {} bytes, expected a word", data.len())))
+}
+
+impl AccountAbstraction {
+    /// Fails only on unusable EntryPoint / factory addresses.
+    pub fn new(
+        config: AccountAbstractionConfig,
+        blockchain: &BlockchainConfig,
+        storage: Arc<WalletStorage>,
+    ) -> Result<Self, WalletError> {
+        let parse = |field: &str, value: &str| {
+            value.parse::<Address>().map_err(|_| {
+                WalletError::ConfigError(format!("account_abstraction.{} is not an address: {:?}", field, value))
+            })
+        };
+        Ok(Self {
+            entry_point: parse("entry_point", &config.entry_point)?,
+            factory: parse("account_factory", &config.account_factory)?,
+            config,
+            networks: blockchain.networks.clone(),
+            storage,
+        })
+    }
+
+    pub fn config(&self) -> &AccountAbstractionConfig {
+        &self.config
+    }
+
+    pub fn entry_point(&self) -> Address {
+        self.entry_point
+    }
+
+    fn network(&self, network: &str) -> Result<AaNetwork<'_>, Erc4337Error> {
+        let not_configured = || Erc4337Error::NotConfigured(network.to_string());
+        let bundler_url = self.config.bundler_urls.get(network).ok_or_else(not_configured)?;
+        let config = self.networks.get(network).ok_or_else(not_configured)?;
+        Ok(AaNetwork { config, bundler: BundlerClient::new(bundler_url.clone()) })
+    }
+
+    fn provider(network: &AaNetwork<'_>) -> Result<Provider<Http>, Erc4337Error> {
+        Provider::<Http>::try_from(network.config.rpc_url.as_str())
+            .map_err(|e| Erc4337Error::Rpc(format!("invalid rpc url: {}", e)))
+    }
+
+    async fn eth_call(provider: &Provider<Http>, to: Address, data: Bytes) -> Result<[u8; 32], Erc4337Error> {
+        let tx: TypedTransaction = TransactionRequest::new().to(to).data(data).into();
+        let output = provider.call(&tx, None).await.map_err(|e| Erc4337Error::Rpc(e.to_string()))?;
+        first_word(&output)
+    }
+
+    async fn smart_account(&self, provider: &Provider<Http>, owner: Address) -> Result<SmartAccount, Erc4337Error> {
+        let salt = U256::from(self.config.account_salt);
+        let word = Self::eth_call(provider, self.factory, user_operation::encode_get_address(owner, salt)).await?;
+        let address = Address::from_slice(&word[12..]);
+        let code = provider.get_code(address, None).await.map_err(|e| Erc4337Error::Rpc(e.to_string()))?;
+        Ok(SmartAccount { address, deployed: !code.is_empty() })
+    }
+
+    /// Smart account of `owner` on `network` (counterfactual until deployed)
+    pub async fn account_for(&self, network: &str, owner: Address) -> Result<SmartAccount, Erc4337Error> {
+        let network = self.network(network)?;
+        self.smart_account(&Self::provider(&network)?, owner).await
+    }
+
+    /// Builds, signs and submits one UserOperation executing `request` from
+    /// `owner`'s account, and records it as pending.
+    pub async fn send(&self, owner: &LocalWallet, request: &AaSendRequest<'_>) -> Result<UserOpSubmission, Erc4337Error> {
+        let network = self.network(request.network)?;
+        let provider = Self::provider(&network)?;
+        let owner_address = owner.address();
+
+        let account = self.smart_account(&provider, owner_address).await?;
+        let (nonce, init_code) = if account.deployed {
+            let word = Self::eth_call(
+                &provider,
+                self.entry_point,
+                user_operation::encode_get_nonce(account.address, U256::zero()),
+            )
+            .await?;
+            (U256::from_big_endian(&word), Bytes::new())
+        } else {
+            (U256::zero(), init_code(self.factory, owner_address, U256::from(self.config.account_salt)))
+        };
+        let gas_price = provider.get_gas_price().await.map_err(|e| Erc4337Error::Rpc(e.to_string()))?;
+
+        let mut op = UserOperation {
+            sender: account.address,
+            nonce,
+            init_code,
+            call_data: encode_execute(request.to, request.value, &request.data),
+            max_fee_per_gas: gas_price,
+            max_priority_fee_per_gas: gas_price,
+            paymaster_and_data: request.paymaster_and_data.clone(),
+            signature: dummy_signature(),
+            ..Default::default()
+        };
+        let estimate = network
+            .bundler
+            .estimate_user_operation_gas(&op, self.entry_point)
+            .await
+            .map_err(|e| Erc4337Error::from_bundler(e, |aa_code, message| Erc4337Error::GasEstimation { aa_code, message }))?;
+        op.call_gas_limit = estimate.call_gas_limit;
+        op.verification_gas_limit = estimate.verification_gas_limit;
+        op.pre_verification_gas = estimate.pre_verification_gas;
+
+        let local_hash = op.sign(owner, self.entry_point, network.config.chain_id)?;
+        let user_op_hash = network
+            .bundler
+            .send_user_operation(&op, self.entry_point)
+            .await
+            .map_err(|e| Erc4337Error::from_bundler(e, |aa_code, message| Erc4337Error::Rejected { aa_code, message }))?;
+        if user_op_hash != local_hash {
+            // receipts are keyed by the bundler's hash, so track that one
+            warn!(
+                "bundler {} returned userOpHash {:?}, computed {:?}: entry point or chain id mismatch?",
+                network.bundler.url(),
+                user_op_hash,
+                local_hash
+            );
+        }
+
+        let user_op_hash = format!("{:?}", user_op_hash);
+        let sender = hex_address(account.address);
+        self.storage
+            .record_user_operation(&NewUserOperation {
+                user_op_hash: &user_op_hash,
+                wallet_name: request.wallet_name,
+                network: request.network,
+                sender: &sender,
+                entry_point: &hex_address(self.entry_point),
+                to_address: &hex_address(request.to),
+                value: &request.value.to_string(),
+                nonce: &nonce.to_string(),
+            })
+            .await?;
+        info!("UserOperation {} submitted for wallet {} from {}", user_op_hash, request.wallet_name, sender);
+
+        Ok(UserOpSubmission {
+            user_op_hash,
+            sender,
+            nonce: nonce.to_string(),
+            account_deployed: account.deployed,
+            network: request.network.to_string(),
+            status: USER_OP_PENDING.to_string(),
+        })
+    }
+
+    /// Polls the bundler once for a pending op and records its inclusion.
+    /// Returns the current record (`None` if the op is unknown).
+    pub async fn refresh(&self, user_op_hash: &str) -> Result<Option<UserOperationRecord>, Erc4337Error> {
+        let Some(record) = self.storage.user_operation(user_op_hash).await? else {
+            return Ok(None);
+        };
+        if record.status != USER_OP_PENDING {
+            return Ok(Some(record));
+        }
+        let hash: H256 = user_op_hash
+            .parse()
+            .map_err(|_| Erc4337Error::InvalidRequest(format!("bad userOpHash {}", user_op_hash)))?;
+        let network = self.network(&record.network)?;
+        let Some(receipt) = network
+            .bundler
+            .get_user_operation_receipt(hash)
+            .await
+            .map_err(|e| Erc4337Error::Bundler(e.to_string()))?
+        else {
+            return Ok(Some(record));
+        };
+
+        let (status, tx_status) =
+            if receipt.success { (USER_OP_INCLUDED, "confirmed") } else { (USER_OP_REVERTED, "failed") };
+        let wallet_id = self
+            .storage
+            .wallet_id(&record.wallet_name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wallet not found: {}", record.wallet_name))?;
+        let value = U256::from_dec_str(&record.value).unwrap_or_default();
+        let now = chrono::Utc::now();
+        let transaction = TransactionRecord {
+            id: record.user_op_hash.clone(),
+            wallet_id,
+            tx_hash: format!("{:?}", receipt.receipt.transaction_hash),
+            network: record.network.clone(),
+            from_address: record.sender.clone(),
+            to_address: record.to_address.clone(),
+            amount: ethers::utils::format_ether(value),
+            fee: ethers::utils::format_ether(receipt.actual_gas_cost),
+            status: tx_status.to_string(),
+            created_at: chrono::DateTime::from_timestamp(record.created_at, 0).unwrap_or(now),
+            confirmed_at: Some(now),
+            integrity_hash: String::new(),
+            fee_actual: None,
+            block_number: None,
+        };
+        if self
+            .storage
+            .complete_user_operation(
+                user_op_hash,
+                status,
+                &receipt.actual_gas_cost.to_string(),
+                receipt.reason.as_deref(),
+                &transaction,
+            )
+            .await?
+        {
+            info!("UserOperation {} {} in {}", user_op_hash, status, transaction.tx_hash);
+        }
+        Ok(self.storage.user_operation(user_op_hash).await?)
+    }
+
+    /// Polls `user_op_hash` every `receipt_poll_secs` until it is included or
+    /// `receipt_timeout_secs` pass.
+    pub fn track(self: Arc<Self>, user_op_hash: String) -> JoinHandle<()> {
+        spawn_named("user_op_tracker", async move {
+            let poll = Duration::from_secs(self.config.receipt_poll_secs.max(1));
+            let deadline = tokio::time::Instant::now() + Duration::from_secs(self.config.receipt_timeout_secs);
+            while tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(poll).await;
+                match self.refresh(&user_op_hash).await {
+                    Ok(Some(record)) if record.status == USER_OP_PENDING => {}
+                    Ok(_) => return,
+                    Err(e) => warn!("UserOperation {} receipt check failed: {}", user_op_hash, e),
+                }
+            }
+            warn!("UserOperation {} still pending after {}s", user_op_hash, self.config.receipt_timeout_secs);
+        })
+    }
+}
@@ -0,0 +1,127 @@
+//! EntryPoint v0.6 `UserOperation`: userOpHash, signing and the
+//! SimpleAccount / SimpleAccountFactory calldata the wallet builds.
+//!
+//! ```text
+//! userOpHash = keccak256(abi.encode(keccak256(pack(op)), entryPoint, chainId))
+//! pack(op)   = abi.encode(sender, nonce, keccak256(initCode), keccak256(callData),
+//!                         callGasLimit, verificationGasLimit, preVerificationGas,
+//!                         maxFeePerGas, maxPriorityFeePerGas, keccak256(paymasterAndData))
+//! ```
+
+use ethers::abi::{self, Token};
+use ethers::signers::LocalWallet;
+use ethers::types::{Address, Bytes, H256, U256};
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+
+use crate::core::abi::selector_from_signature;
+use crate::core::errors::WalletError;
+
+pub const EXECUTE_SIGNATURE: &str = "execute(address,uint256,bytes)";
+pub const CREATE_ACCOUNT_SIGNATURE: &str = "createAccount(address,uint256)";
+pub const GET_ADDRESS_SIGNATURE: &str = "getAddress(address,uint256)";
+pub const GET_NONCE_SIGNATURE: &str = "getNonce(address,uint192)";
+
+/// Throwaway key for the placeholder signature sent with gas estimation
+const DUMMY_SIGNER_KEY: [u8; 32] = [0x11; 32];
+
+/// A UserOperation as sent to a bundler (`eth_sendUserOperation` params use
+/// the same camelCase names with `0x` hex quantities)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: Bytes,
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub paymaster_and_data: Bytes,
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    /// `UserOperationLib.pack`: the op without its signature, dynamic fields hashed
+    pub fn pack(&self) -> Vec<u8> {
+        let hashed = |data: &Bytes| Token::FixedBytes(keccak256(data).to_vec());
+        abi::encode(&[
+            Token::Address(self.sender),
+            Token::Uint(self.nonce),
+            hashed(&self.init_code),
+            hashed(&self.call_data),
+            Token::Uint(self.call_gas_limit),
+            Token::Uint(self.verification_gas_limit),
+            Token::Uint(self.pre_verification_gas),
+            Token::Uint(self.max_fee_per_gas),
+            Token::Uint(self.max_priority_fee_per_gas),
+            hashed(&self.paymaster_and_data),
+        ])
+    }
+
+    /// `EntryPoint.getUserOpHash(op)` on chain `chain_id`
+    pub fn hash(&self, entry_point: Address, chain_id: u64) -> H256 {
+        H256(keccak256(abi::encode(&[
+            Token::FixedBytes(keccak256(self.pack()).to_vec()),
+            Token::Address(entry_point),
+            Token::Uint(U256::from(chain_id)),
+        ])))
+    }
+
+    /// Signs the userOpHash as SimpleAccount verifies it: an EIP-191
+    /// personal-message signature by the owner key.
+    pub fn sign(&mut self, owner: &LocalWallet, entry_point: Address, chain_id: u64) -> Result<H256, WalletError> {
+        let hash = self.hash(entry_point, chain_id);
+        let digest = ethers::utils::hash_message(hash.as_bytes());
+        let signature = owner
+            .sign_hash(digest)
+            .map_err(|e| WalletError::CryptoError(format!("Failed to sign user operation: {}", e)))?;
+        self.signature = Bytes::from(signature.to_vec());
+        Ok(hash)
+    }
+}
+
+/// A well-formed signature that recovers to an unrelated address, so the
+/// account's validation runs its full path during `eth_estimateUserOperationGas`
+/// without reverting.
+pub fn dummy_signature() -> Bytes {
+    let signer = LocalWallet::from_bytes(&DUMMY_SIGNER_KEY).expect("constant key is a valid secp256k1 scalar");
+    let signature = signer.sign_hash(H256::zero()).expect("signing a constant digest");
+    Bytes::from(signature.to_vec())
+}
+
+/// SimpleAccount `execute(dest, value, func)`
+pub fn encode_execute(dest: Address, value: U256, data: &[u8]) -> Bytes {
+    call(EXECUTE_SIGNATURE, &[Token::Address(dest), Token::Uint(value), Token::Bytes(data.to_vec())])
+}
+
+/// SimpleAccountFactory `createAccount(owner, salt)`
+pub fn encode_create_account(owner: Address, salt: U256) -> Bytes {
+    call(CREATE_ACCOUNT_SIGNATURE, &[Token::Address(owner), Token::Uint(salt)])
+}
+
+/// SimpleAccountFactory `getAddress(owner, salt)` (counterfactual address)
+pub fn encode_get_address(owner: Address, salt: U256) -> Bytes {
+    call(GET_ADDRESS_SIGNATURE, &[Token::Address(owner), Token::Uint(salt)])
+}
+
+/// EntryPoint `getNonce(sender, key)`
+pub fn encode_get_nonce(sender: Address, key: U256) -> Bytes {
+    call(GET_NONCE_SIGNATURE, &[Token::Address(sender), Token::Uint(key)])
+}
+
+/// `initCode` deploying the owner's account on first use: factory address
+/// followed by the `createAccount` calldata
+pub fn init_code(factory: Address, owner: Address, salt: U256) -> Bytes {
+    let mut code = factory.as_bytes().to_vec();
+    code.extend_from_slice(&encode_create_account(owner, salt));
+    Bytes::from(code)
+}
+
+fn call(signature: &str, args: &[Token]) -> Bytes {
+    let mut data = selector_from_signature(signature).to_vec();
+    data.extend_from_slice(&abi::encode(args));
+    Bytes::from(data)
+}
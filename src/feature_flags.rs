@@ -0,0 +1,371 @@
+//! Runtime feature flags for gradually enabling risky subsystems.
+//!
+//! Flags are declared in code as [`Flag`]; names that are not declared are
+//! rejected. A flag is decided by, in order: its rule for the wallet, its
+//! global rule, and its default (declared here, overridden by
+//! `feature_flags.defaults` in the config). A global rule with a
+//! `rollout_percent` is on for that share of wallets, picked by hashing the
+//! flag and wallet names: a wallet keeps its assignment across restarts and
+//! instances, and each flag rolls out to a different set of wallets.
+//!
+//! Rules are cached in-process. The cache reads the `feature_flags` cache
+//! epoch at most once per `epoch_check_ms` and reloads when it moved, so a
+//! change made on another instance applies within that interval; changes
+//! made through this handle apply to it immediately. While the database
+//! cannot be read, cached rules keep being served, or the defaults if none
+//! were ever loaded.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::core::config::WalletConfig;
+use crate::storage::{FeatureFlagRecord, NewFeatureFlag, WalletStorage};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Flag {
+    /// Anomaly rules refuse the bundle and meta-transaction counterparties
+    /// they flag; when off they only log them.
+    AnomalyBlocking,
+    /// ERC-4337 sends (`POST /api/wallets/:name/aa/send`).
+    AccountAbstraction,
+    /// The dead-man's switch evaluator warns, triggers and sweeps.
+    DeadmanSwitch,
+}
+
+impl Flag {
+    pub const ALL: [Flag; 3] = [Flag::AnomalyBlocking, Flag::AccountAbstraction, Flag::DeadmanSwitch];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Flag::AnomalyBlocking => "anomaly_blocking",
+            Flag::AccountAbstraction => "account_abstraction",
+            Flag::DeadmanSwitch => "deadman_switch",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Flag::AnomalyBlocking => "Refuse bundle and relay counterparties flagged by the anomaly rules",
+            Flag::AccountAbstraction => "Allow ERC-4337 user operation sends",
+            Flag::DeadmanSwitch => "Evaluate dead-man's switch policies and sweep triggered ones",
+        }
+    }
+
+    /// Default before the config is applied; the subsystems behaved this way
+    /// before they had a flag.
+    fn builtin_default(self, config: &WalletConfig) -> bool {
+        match self {
+            Flag::AnomalyBlocking | Flag::AccountAbstraction => true,
+            Flag::DeadmanSwitch => config.security.deadman.enabled,
+        }
+    }
+}
+
+impl fmt::Display for Flag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Flag {
+    type Err = FlagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Flag::ALL.into_iter().find(|flag| flag.name() == s).ok_or_else(|| FlagError::Unknown(s.to_string()))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FlagError {
+    #[error("Unknown feature flag: {0}")]
+    Unknown(String),
+    #[error("Invalid feature flag rule: {0}")]
+    InvalidRule(String),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+/// A declared flag with its default, its stored rules and the decision they
+/// make for one wallet (or for callers not tied to a wallet).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FlagState {
+    pub flag: Flag,
+    pub description: &'static str,
+    pub default: bool,
+    pub enabled: bool,
+    pub rules: Vec<FeatureFlagRecord>,
+}
+
+/// Bucket 0-99 of `wallet_id` for `flag`; a rollout of `p` percent covers
+/// the buckets below `p`.
+pub fn bucket(flag: Flag, wallet_id: &str) -> u8 {
+    let digest = Sha256::new().chain_update(flag.name()).chain_update(b":").chain_update(wallet_id).finalize();
+    let mut head = [0u8; 8];
+    head.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(head) % 100) as u8
+}
+
+/// Decision for `flag` under `rules` (rules of other flags are ignored).
+///
+/// A partial rollout is off for callers that are not tied to a wallet.
+pub fn evaluate(flag: Flag, wallet_id: Option<&str>, rules: &[FeatureFlagRecord], default: bool) -> bool {
+    let mut global = None;
+    for rule in rules.iter().filter(|r| r.flag == flag.name()) {
+        match (rule.wallet_id.as_deref(), wallet_id) {
+            (Some(scope), Some(wallet)) if scope == wallet => return rule.enabled,
+            (None, _) => global = Some(rule),
+            _ => {}
+        }
+    }
+    match global {
+        None => default,
+        Some(rule) if !rule.enabled => false,
+        Some(rule) => match (rule.rollout_percent, wallet_id) {
+            (None, _) => true,
+            (Some(percent), Some(wallet)) => i64::from(bucket(flag, wallet)) < percent,
+            (Some(percent), None) => percent >= 100,
+        },
+    }
+}
+
+#[derive(Debug, Default)]
+struct RuleCache {
+    /// Last epoch read; `None` until the first read or after a local change
+    epoch: Option<i64>,
+    checked_at: Option<DateTime<Utc>>,
+    rules: Option<Arc<Vec<FeatureFlagRecord>>>,
+    /// Bumped by every flush; a load that started before one is not stored
+    generation: u64,
+}
+
+pub struct FeatureFlags {
+    storage: Arc<WalletStorage>,
+    defaults: HashMap<Flag, bool>,
+    epoch_check_interval: Duration,
+    cache: Mutex<RuleCache>,
+}
+
+impl FeatureFlags {
+    /// Flags with the defaults of `WalletConfig::default()`.
+    pub fn new(storage: Arc<WalletStorage>) -> Self {
+        let config = WalletConfig::default();
+        Self {
+            storage,
+            defaults: Flag::ALL.into_iter().map(|flag| (flag, flag.builtin_default(&config))).collect(),
+            epoch_check_interval: Duration::from_millis(config.feature_flags.epoch_check_ms),
+            cache: Mutex::new(RuleCache::default()),
+        }
+    }
+
+    /// Fails on a flag name in `feature_flags.defaults` that is not declared.
+    pub fn from_config(config: &WalletConfig, storage: Arc<WalletStorage>) -> Result<Self, FlagError> {
+        let mut flags = Self::new(storage)
+            .with_epoch_check_interval(Duration::from_millis(config.feature_flags.epoch_check_ms));
+        for flag in Flag::ALL {
+            flags.defaults.insert(flag, flag.builtin_default(config));
+        }
+        for (name, enabled) in &config.feature_flags.defaults {
+            flags.defaults.insert(name.parse()?, *enabled);
+        }
+        Ok(flags)
+    }
+
+    pub fn with_default(mut self, flag: Flag, enabled: bool) -> Self {
+        self.defaults.insert(flag, enabled);
+        self
+    }
+
+    pub fn with_epoch_check_interval(mut self, interval: Duration) -> Self {
+        self.epoch_check_interval = interval;
+        self
+    }
+
+    pub fn default_for(&self, flag: Flag) -> bool {
+        self.defaults.get(&flag).copied().unwrap_or(false)
+    }
+
+    /// Whether `flag` is on for `wallet_id`, or for a caller not tied to a
+    /// wallet when it is `None`.
+    pub async fn is_enabled(&self, flag: Flag, wallet_id: Option<&str>) -> bool {
+        let default = self.default_for(flag);
+        match self.rules().await {
+            Some(rules) => evaluate(flag, wallet_id, &rules, default),
+            None => default,
+        }
+    }
+
+    /// Every declared flag, decided for `wallet_id`. Reads the rules from the
+    /// database rather than the cache.
+    pub async fn list(&self, wallet_id: Option<&str>) -> Result<Vec<FlagState>, FlagError> {
+        let rules = self.storage.feature_flags().await?;
+        Ok(Flag::ALL
+            .into_iter()
+            .map(|flag| {
+                let default = self.default_for(flag);
+                FlagState {
+                    flag,
+                    description: flag.description(),
+                    default,
+                    enabled: evaluate(flag, wallet_id, &rules, default),
+                    rules: rules.iter().filter(|r| r.flag == flag.name()).cloned().collect(),
+                }
+            })
+            .collect())
+    }
+
+    /// Stores the rule of `flag` for `wallet_id` (the global rule when
+    /// `None`). Only the global rule may carry a `rollout_percent`.
+    pub async fn set(
+        &self,
+        flag: Flag,
+        wallet_id: Option<&str>,
+        enabled: bool,
+        rollout_percent: Option<u8>,
+        updated_by: &str,
+    ) -> Result<FeatureFlagRecord, FlagError> {
+        if rollout_percent.is_some_and(|p| p > 100) {
+            return Err(FlagError::InvalidRule("rollout_percent must be between 0 and 100".to_string()));
+        }
+        if wallet_id.is_some() && rollout_percent.is_some() {
+            return Err(FlagError::InvalidRule("rollout_percent applies to the global rule only".to_string()));
+        }
+        let record = self
+            .storage
+            .set_feature_flag(&NewFeatureFlag { flag: flag.name(), wallet_id, enabled, rollout_percent, updated_by })
+            .await?;
+        self.flush();
+        Ok(record)
+    }
+
+    /// Drops the rule of `flag` for `wallet_id` (or the global one);
+    /// `false` if there was none.
+    pub async fn clear(&self, flag: Flag, wallet_id: Option<&str>, updated_by: &str) -> Result<bool, FlagError> {
+        let cleared = self.storage.clear_feature_flag(flag.name(), wallet_id, updated_by).await?;
+        self.flush();
+        Ok(cleared)
+    }
+
+    /// Drops the cached rules. The epoch is forgotten too, so the bump made
+    /// by a change through this handle is not taken for a foreign one.
+    pub fn flush(&self) {
+        let mut cache = self.cache.lock();
+        cache.rules = None;
+        cache.epoch = None;
+        cache.checked_at = None;
+        cache.generation += 1;
+    }
+
+    /// Current rules, loading them when the epoch moved; `None` when they
+    /// cannot be loaded.
+    async fn rules(&self) -> Option<Arc<Vec<FeatureFlagRecord>>> {
+        let now = self.storage.clock().now();
+        let due = match self.cache.lock().checked_at {
+            Some(at) => (now - at).to_std().map(|gap| gap >= self.epoch_check_interval).unwrap_or(false),
+            None => true,
+        };
+        if due {
+            let epoch = self.storage.feature_flags_epoch().await;
+            let mut cache = self.cache.lock();
+            match epoch {
+                Ok(epoch) => {
+                    if cache.epoch != Some(epoch) {
+                        cache.rules = None;
+                    }
+                    cache.epoch = Some(epoch);
+                }
+                Err(e) => warn!("feature flags: epoch check failed, serving cached rules: {}", e),
+            }
+            cache.checked_at = Some(now);
+        }
+
+        let generation = {
+            let cache = self.cache.lock();
+            if let Some(rules) = &cache.rules {
+                return Some(rules.clone());
+            }
+            cache.generation
+        };
+        match self.storage.feature_flags().await {
+            Ok(rules) => {
+                let rules = Arc::new(rules);
+                let mut cache = self.cache.lock();
+                if cache.generation == generation {
+                    cache.rules = Some(rules.clone());
+                }
+                Some(rules)
+            }
+            Err(e) => {
+                warn!("feature flags: failed to load rules, using defaults: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(flag: Flag, wallet_id: Option<&str>, enabled: bool, rollout_percent: Option<i64>) -> FeatureFlagRecord {
+        FeatureFlagRecord {
+            flag: flag.name().to_string(),
+            wallet_id: wallet_id.map(str::to_string),
+            enabled,
+            rollout_percent,
+            updated_by: "admin".to_string(),
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_wallet_rule_overrides_global_rule_and_default() {
+        let flag = Flag::AnomalyBlocking;
+        assert!(evaluate(flag, Some("treasury"), &[], true));
+        let rules = [rule(flag, None, false, None), rule(flag, Some("treasury"), true, None)];
+        assert!(evaluate(flag, Some("treasury"), &rules, true));
+        assert!(!evaluate(flag, Some("payroll"), &rules, true));
+        assert!(!evaluate(flag, None, &rules, true));
+        // rules of other flags do not apply
+        assert!(evaluate(Flag::DeadmanSwitch, Some("payroll"), &rules, true));
+    }
+
+    #[test]
+    fn test_rollout_buckets_are_stable_and_proportional() {
+        let flag = Flag::AccountAbstraction;
+        assert_eq!(bucket(flag, "treasury"), bucket(flag, "treasury"));
+        let wallets: Vec<String> = (0..2000).map(|i| format!("wallet-{}", i)).collect();
+        let rules = [rule(flag, None, true, Some(25))];
+        let on = wallets.iter().filter(|w| evaluate(flag, Some(w), &rules, false)).count();
+        assert!((400..600).contains(&on), "{} of 2000 wallets in a 25% rollout", on);
+
+        // widening the rollout keeps every wallet that was already in
+        let wider = [rule(flag, None, true, Some(50))];
+        assert!(wallets
+            .iter()
+            .filter(|w| evaluate(flag, Some(w), &rules, false))
+            .all(|w| evaluate(flag, Some(w), &wider, false)));
+
+        assert!(!evaluate(flag, None, &rules, true));
+        assert!(evaluate(flag, None, &[rule(flag, None, true, Some(100))], false));
+        assert!(wallets.iter().all(|w| !evaluate(flag, Some(w), &[rule(flag, None, true, Some(0))], true)));
+    }
+
+    #[test]
+    fn test_flag_names_round_trip() {
+        for flag in Flag::ALL {
+            assert_eq!(flag.name().parse::<Flag>().unwrap(), flag);
+            assert_eq!(serde_json::to_value(flag).unwrap(), flag.name());
+        }
+        assert!(matches!("auto_approve_everything".parse::<Flag>(), Err(FlagError::Unknown(_))));
+    }
+}
@@ -1,2 +1,14 @@
 hello = Hello
 wallet-created = Wallet "{ $name }" created successfully.
+error-auth-failed = Authentication failed.
+error-invalid-password = The wallet password is incorrect.
+error-weak-password = The password is too weak.
+error-invalid-address = The address is invalid.
+error-invalid-amount = The amount is invalid.
+error-unsupported-network = This network is not supported.
+error-wallet-not-found = Wallet not found.
+error-wallet-exists = A wallet with this name already exists.
+error-insufficient-balance = Insufficient balance.
+error-server-busy = The server is busy. Please try again shortly.
+error-network-error = The blockchain network could not be reached.
+error-internal = Something went wrong. Please try again later.
@@ -1,2 +1,14 @@
 hello = 你好
 wallet-created = 钱包 "{ $name }" 创建成功。
+error-auth-failed = 身份验证失败。
+error-invalid-password = 钱包密码错误。
+error-weak-password = 密码强度不足。
+error-invalid-address = 地址无效。
+error-invalid-amount = 金额无效。
+error-unsupported-network = 不支持该网络。
+error-wallet-not-found = 钱包不存在。
+error-wallet-exists = 同名钱包已存在。
+error-insufficient-balance = 余额不足。
+error-server-busy = 服务器繁忙，请稍后重试。
+error-network-error = 无法连接区块链网络。
+error-internal = 出现错误，请稍后重试。
@@ -77,6 +77,10 @@ impl I18nManager {
         }
     }
 
+    pub fn default_language(&self) -> &str {
+        &self.default_language
+    }
+
     pub fn get_supported_languages(&self) -> Vec<String> {
         self.bundles.keys().cloned().collect()
     }
@@ -0,0 +1,195 @@
+//! JSON-RPC [`BroadcastChain`] backed by the wallet manager's network config.
+//!
+//! Block heads of networks whose RPC URL is `ws://` or `wss://` come from a
+//! `newHeads` subscription kept open per network; blocks are read over the
+//! same connection. Everything else goes over HTTP.
+
+use async_trait::async_trait;
+use ethers::providers::{Http, Middleware, Provider, Ws};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, BlockNumber, Bytes, TransactionReceipt, H256};
+use futures::StreamExt;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{debug, warn};
+
+use super::{BroadcastChain, ChainBlock};
+use crate::core::errors::WalletError;
+use crate::core::wallet_manager::WalletManager;
+use crate::ops::incidents::spawn_named;
+
+pub struct RpcBroadcastChain {
+    wallet_manager: Arc<WalletManager>,
+    heads: Mutex<HashMap<String, HeadFeed>>,
+}
+
+/// Open `newHeads` subscription of one network
+#[derive(Clone)]
+struct HeadFeed {
+    provider: Provider<Ws>,
+    head: watch::Receiver<u64>,
+}
+
+fn is_websocket(rpc_url: &str) -> bool {
+    rpc_url.starts_with("ws://") || rpc_url.starts_with("wss://")
+}
+
+async fn fetch_block<M: Middleware>(provider: &M, number: u64) -> Result<Option<ChainBlock>, WalletError> {
+    let block = provider
+        .get_block(number)
+        .await
+        .map_err(|e| WalletError::NetworkError(format!("Failed to get block {}: {}", number, e)))?;
+    // a block without a hash is still pending
+    Ok(block.and_then(|b| {
+        Some(ChainBlock {
+            number: b.number?.as_u64(),
+            hash: b.hash?,
+            parent_hash: b.parent_hash,
+            transactions: b.transactions,
+        })
+    }))
+}
+
+impl RpcBroadcastChain {
+    pub fn new(wallet_manager: Arc<WalletManager>) -> Self {
+        Self { wallet_manager, heads: Mutex::new(HashMap::new()) }
+    }
+
+    /// The head subscription of `network`, (re)connecting when there is none
+    /// or its stream has ended.
+    async fn head_feed(&self, network: &str, rpc_url: &str) -> Result<HeadFeed, WalletError> {
+        if let Some(feed) = self.heads.lock().get(network).filter(|f| f.head.has_changed().is_ok()) {
+            return Ok(feed.clone());
+        }
+        let provider = Provider::<Ws>::connect(rpc_url)
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Failed to connect to RPC: {}", e)))?;
+        let latest = provider
+            .get_block_number()
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Failed to get block number: {}", e)))?;
+        let (tx, head) = watch::channel(latest.as_u64());
+        let subscriber = provider.clone();
+        let name = network.to_string();
+        spawn_named("block_subscription", async move {
+            let mut stream = match subscriber.subscribe_blocks().await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("newHeads subscription on {} failed: {}", name, e);
+                    return;
+                }
+            };
+            while let Some(block) = stream.next().await {
+                if let Some(number) = block.number {
+                    tx.send_if_modified(|head| {
+                        let advanced = number.as_u64() > *head;
+                        *head = (*head).max(number.as_u64());
+                        advanced
+                    });
+                }
+                if tx.is_closed() {
+                    break;
+                }
+            }
+            // dropping `tx` makes the next lookup reconnect
+            debug!("newHeads subscription on {} ended", name);
+        });
+        let feed = HeadFeed { provider, head };
+        self.heads.lock().insert(network.to_string(), feed.clone());
+        Ok(feed)
+    }
+
+    fn provider(&self, network: &str) -> Result<Provider<Http>, WalletError> {
+        let rpc_url = self.wallet_manager.get_rpc_url(network)?;
+        Provider::<Http>::try_from(rpc_url)
+            .map_err(|e| WalletError::NetworkError(format!("Failed to connect to RPC: {}", e)))
+    }
+}
+
+#[async_trait]
+impl BroadcastChain for RpcBroadcastChain {
+    async fn prepare(&self, network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let provider = self.provider(network)?;
+        let chain_id = provider
+            .get_chainid()
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Failed to get chain_id: {}", e)))?;
+        tx.set_chain_id(chain_id.as_u64());
+        // a nonce assigned by the caller (batch sends) is kept
+        if let (None, Some(from)) = (tx.nonce(), tx.from().copied()) {
+            let nonce = provider
+                .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+                .await
+                .map_err(|e| WalletError::NetworkError(format!("Failed to get transaction count: {}", e)))?;
+            tx.set_nonce(nonce);
+        }
+        provider
+            .fill_transaction(tx, None)
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Failed to prepare transaction: {}", e)))
+    }
+
+    async fn send_raw_transaction(&self, network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        let provider = self.provider(network)?;
+        let pending = provider
+            .send_raw_transaction(raw)
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Failed to broadcast transaction: {}", e)))?;
+        Ok(pending.tx_hash())
+    }
+
+    async fn transaction_known(&self, network: &str, tx_hash: H256) -> Result<bool, WalletError> {
+        let tx = self
+            .provider(network)?
+            .get_transaction(tx_hash)
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Failed to look up transaction: {}", e)))?;
+        Ok(tx.is_some())
+    }
+
+    async fn transaction_receipt(
+        &self,
+        network: &str,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        self.provider(network)?
+            .get_transaction_receipt(tx_hash)
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Failed to get transaction receipt: {}", e)))
+    }
+
+    async fn transaction_count(&self, network: &str, address: Address) -> Result<u64, WalletError> {
+        let count = self
+            .provider(network)?
+            .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Failed to get transaction count: {}", e)))?;
+        Ok(count.low_u64())
+    }
+
+    async fn block_number(&self, network: &str) -> Result<u64, WalletError> {
+        let rpc_url = self.wallet_manager.get_rpc_url(network)?;
+        if is_websocket(rpc_url) {
+            let feed = self.head_feed(network, rpc_url).await?;
+            let head = *feed.head.borrow();
+            return Ok(head);
+        }
+        let number = self
+            .provider(network)?
+            .get_block_number()
+            .await
+            .map_err(|e| WalletError::NetworkError(format!("Failed to get block number: {}", e)))?;
+        Ok(number.as_u64())
+    }
+
+    async fn block(&self, network: &str, number: u64) -> Result<Option<ChainBlock>, WalletError> {
+        let rpc_url = self.wallet_manager.get_rpc_url(network)?;
+        if is_websocket(rpc_url) {
+            let feed = self.head_feed(network, rpc_url).await?;
+            return fetch_block(&feed.provider, number).await;
+        }
+        fetch_block(&self.provider(network)?, number).await
+    }
+}
@@ -0,0 +1,291 @@
+//! Decision context of a server-signed send
+//!
+//! Auditors need to know which limits and policies let a transaction through
+//! when it was sent, not what they are today. Each check on the send path
+//! records what it compared against into a [`DecisionContext`] as it runs:
+//! the token cap, the network allowlist, the review threshold, the anomaly
+//! score and rule-set hash, and so on. The context then goes with the signing
+//! intent, and [`SigningIntentLog`](super::SigningIntentLog) writes it to
+//! `transaction_decisions` together with the transaction row. Nothing is
+//! looked up again at that point, so the snapshot always matches the checks
+//! that actually ran.
+//!
+//! Only policy values are recorded: thresholds, ids, scores and hashes. The
+//! stored form still goes through [`redact_json`], so a field added later
+//! under a secret-sounding name is masked rather than persisted.
+
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::U256;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::anomaly_detection::AnomalyResult;
+use crate::approvals::ReviewCheck;
+use crate::blockchain::recipient_guard::RecipientClass;
+use crate::security::redaction::redact_json;
+
+/// Accumulates the outcome of each check a send passed.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DecisionContext {
+    /// User id, or `admin` for API-key callers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    requested_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token: Option<TokenLimit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    network: Option<NetworkPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicate: Option<DuplicateCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recipient: Option<RecipientClass>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    review: Option<ReviewCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    approval: Option<ApprovalRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delegation: Option<DelegationUsage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spending_limit: Option<SpendingLimitCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    anomaly: Option<AnomalyCheck>,
+    /// Feature flags consulted, with the state they had for this wallet
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    feature_flags: BTreeMap<String, bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fee: Option<FeeParameters>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bundle_id: Option<String>,
+}
+
+/// Wallet-scoped token the send was authorized with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenLimit {
+    pub token_id: String,
+    /// Per-transaction cap in decimal whole units; `None` when uncapped
+    pub amount_cap: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NetworkPolicy {
+    pub network: String,
+    /// The wallet's allowlist; `None` when the wallet is unrestricted
+    pub allowed: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateCheck {
+    /// 0 when duplicate detection is off
+    pub window_secs: u64,
+    pub allow_duplicate: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApprovalRef {
+    pub approval_id: String,
+    pub requested_by: String,
+    pub approved_by: String,
+}
+
+/// Delegation the send was made under and its budget after this send
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DelegationUsage {
+    pub delegation_id: String,
+    /// Wei
+    pub per_tx_cap: String,
+    pub budget_total: String,
+    pub budget_spent: String,
+}
+
+/// Wallet spending limit on the network and what had gone out in the 24
+/// hours before this send, all in wei
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpendingLimitCheck {
+    pub max_per_tx: Option<String>,
+    pub max_per_day: Option<String>,
+    /// `None` when there is no daily cap and the total was not read
+    pub spent_last_day: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnomalyCheck {
+    /// Highest score among the screened counterparties
+    pub score: f64,
+    pub threat_level: String,
+    /// [`AnomalyDetector::rules_hash`](crate::anomaly_detection::AnomalyDetector::rules_hash)
+    /// of the rules that produced the score
+    pub rules_hash: String,
+}
+
+/// Fee fields of the transaction as signed, in wei (decimal)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeeParameters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<String>,
+}
+
+impl FeeParameters {
+    pub fn of(tx: &TypedTransaction) -> Self {
+        let wei = |v: Option<&U256>| v.map(U256::to_string);
+        let (gas_price, max_fee_per_gas, max_priority_fee_per_gas) = match tx {
+            TypedTransaction::Eip1559(tx) => {
+                (None, wei(tx.max_fee_per_gas.as_ref()), wei(tx.max_priority_fee_per_gas.as_ref()))
+            }
+            other => (wei(other.gas_price().as_ref()), None, None),
+        };
+        Self { gas_limit: wei(tx.gas()), gas_price, max_fee_per_gas, max_priority_fee_per_gas }
+    }
+}
+
+impl DecisionContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_requester(&mut self, principal: &str) {
+        self.requested_by = Some(principal.to_string());
+    }
+
+    pub fn record_token(&mut self, token_id: &str, amount_cap: Option<&str>) {
+        self.token = Some(TokenLimit { token_id: token_id.to_string(), amount_cap: amount_cap.map(str::to_string) });
+    }
+
+    pub fn record_network(&mut self, network: &str, allowed: Option<Vec<String>>) {
+        self.network = Some(NetworkPolicy { network: network.to_string(), allowed });
+    }
+
+    pub fn record_duplicate_check(&mut self, window_secs: u64, allow_duplicate: bool) {
+        self.duplicate = Some(DuplicateCheck { window_secs, allow_duplicate });
+    }
+
+    pub fn record_recipient(&mut self, class: RecipientClass) {
+        self.recipient = Some(class);
+    }
+
+    /// Classification recorded by the recipient check, if it ran
+    pub fn recipient(&self) -> Option<&RecipientClass> {
+        self.recipient.as_ref()
+    }
+
+    pub fn record_review(&mut self, review: ReviewCheck) {
+        self.review = Some(review);
+    }
+
+    pub fn record_approval(&mut self, approval_id: &str, requested_by: &str, approved_by: &str) {
+        self.approval = Some(ApprovalRef {
+            approval_id: approval_id.to_string(),
+            requested_by: requested_by.to_string(),
+            approved_by: approved_by.to_string(),
+        });
+    }
+
+    pub fn record_delegation(&mut self, delegation_id: &str, per_tx_cap: &str, total: U256, spent: U256) {
+        self.delegation = Some(DelegationUsage {
+            delegation_id: delegation_id.to_string(),
+            per_tx_cap: per_tx_cap.to_string(),
+            budget_total: total.to_string(),
+            budget_spent: spent.to_string(),
+        });
+    }
+
+    pub fn record_spending_limit(&mut self, max_per_tx: Option<u128>, max_per_day: Option<u128>, spent: Option<u128>) {
+        let wei = |v: Option<u128>| v.map(|v| v.to_string());
+        self.spending_limit = Some(SpendingLimitCheck {
+            max_per_tx: wei(max_per_tx),
+            max_per_day: wei(max_per_day),
+            spent_last_day: wei(spent),
+        });
+    }
+
+    /// Keeps the highest-scoring result when several counterparties are screened.
+    pub fn record_anomaly(&mut self, result: &AnomalyResult, rules_hash: &str) {
+        if self.anomaly.as_ref().is_some_and(|a| a.score >= result.score) {
+            return;
+        }
+        self.anomaly = Some(AnomalyCheck {
+            score: result.score,
+            threat_level: format!("{:?}", result.threat_level),
+            rules_hash: rules_hash.to_string(),
+        });
+    }
+
+    pub fn record_flag(&mut self, flag: &str, enabled: bool) {
+        self.feature_flags.insert(flag.to_string(), enabled);
+    }
+
+    pub fn record_fee(&mut self, tx: &TypedTransaction) {
+        self.fee = Some(FeeParameters::of(tx));
+    }
+
+    pub fn record_bundle(&mut self, bundle_id: &str) {
+        self.bundle_id = Some(bundle_id.to_string());
+    }
+
+    /// Compact, redacted JSON as stored with the transaction.
+    pub fn to_stored(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        redact_json(&mut value);
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anomaly_detection::ThreatLevel;
+    use ethers::types::{Eip1559TransactionRequest, TransactionRequest};
+
+    #[test]
+    fn test_unrecorded_checks_are_omitted() {
+        let mut decision = DecisionContext::new();
+        assert_eq!(decision.to_stored(), "{}");
+
+        decision.record_requester("user-1");
+        decision.record_network("eth", None);
+        let stored: serde_json::Value = serde_json::from_str(&decision.to_stored()).unwrap();
+        assert_eq!(
+            stored,
+            serde_json::json!({
+                "requested_by": "user-1",
+                "network": { "network": "eth", "allowed": null },
+            })
+        );
+    }
+
+    #[test]
+    fn test_highest_anomaly_score_wins() {
+        let mut decision = DecisionContext::new();
+        let mut result = AnomalyResult::normal();
+        result.score = 0.4;
+        result.threat_level = ThreatLevel::Low;
+        decision.record_anomaly(&result, "aaaa");
+        result.score = 0.1;
+        decision.record_anomaly(&result, "bbbb");
+
+        let anomaly = decision.anomaly.unwrap();
+        assert_eq!(anomaly.score, 0.4);
+        assert_eq!(anomaly.threat_level, "Low");
+        assert_eq!(anomaly.rules_hash, "aaaa");
+    }
+
+    #[test]
+    fn test_fee_parameters_follow_the_envelope() {
+        let legacy: TypedTransaction = TransactionRequest::new().gas(21_000u64).gas_price(3u64).into();
+        let fee = FeeParameters::of(&legacy);
+        assert_eq!(fee.gas_limit.as_deref(), Some("21000"));
+        assert_eq!(fee.gas_price.as_deref(), Some("3"));
+        assert_eq!(fee.max_fee_per_gas, None);
+
+        let dynamic: TypedTransaction =
+            Eip1559TransactionRequest::new().gas(21_000u64).max_fee_per_gas(9u64).max_priority_fee_per_gas(2u64).into();
+        let fee = FeeParameters::of(&dynamic);
+        assert_eq!(fee.gas_price, None);
+        assert_eq!(fee.max_fee_per_gas.as_deref(), Some("9"));
+        assert_eq!(fee.max_priority_fee_per_gas.as_deref(), Some("2"));
+    }
+}
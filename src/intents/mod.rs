@@ -0,0 +1,683 @@
+//! Write-ahead signing intents
+//!
+//! Server-side signing used to sign and broadcast in one RPC round trip, so a
+//! crash in the middle left no trace: the nonce tracker could hand the same
+//! nonce out again (a replacement or double spend), or the transaction went
+//! out without a `transactions` row. Every managed send now walks an intent
+//! through `signing → signed → broadcast → recorded`, committing each step
+//! before the next side effect:
+//!
+//! | crash after   | what reconciliation does                                 |
+//! |---------------|----------------------------------------------------------|
+//! | `signing`     | nothing was signed: release the nonce                    |
+//! | `signed`      | ask the node for the signed hash; record it or release   |
+//! | `broadcast`   | finish the bookkeeping                                   |
+//!
+//! Reconciliation never re-broadcasts, so it cannot duplicate a transaction.
+//! It runs on startup and via `POST /api/admin/intents/reconcile`.
+//!
+//! Before a managed send is signed, [`SigningIntentLog::check_duplicate`]
+//! refuses a transfer identical to one still in flight or pending within the
+//! configured window (double-clicked send buttons).
+//!
+//! [`SigningIntentLog::send_with_decision`] carries the [`DecisionContext`] of
+//! the checks the send passed on the intent, and the transaction record is
+//! written together with it.
+
+pub mod chain;
+pub mod decision;
+
+use async_trait::async_trait;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionReceipt, H256, U256};
+use ethers::utils::keccak256;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::core::amount::{Amount, AmountError, AssetTag};
+use crate::core::config::TxEncoding;
+use crate::core::errors::WalletError;
+use crate::core::rlp::tx::sign_transaction;
+use crate::monitoring::WalletMetrics;
+use crate::storage::{
+    NewFeeRecord, NewSigningIntent, SigningIntentRecord, TransactionRecord, WalletStorage, INTENT_BROADCAST,
+    INTENT_RECORDED, INTENT_RELEASED, INTENT_SIGNED, INTENT_SIGNING,
+};
+
+pub use chain::RpcBroadcastChain;
+pub use decision::DecisionContext;
+
+/// Intents resolved per reconciliation pass
+pub const MAX_RECONCILE_BATCH: i64 = 500;
+
+/// Default duplicate-send window (seconds)
+pub const DEFAULT_DUPLICATE_WINDOW_SECS: u64 = 120;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IntentError {
+    #[error("Nonce {nonce} of {address} on {network} is held by another signing intent")]
+    NonceInUse { network: String, address: String, nonce: u64 },
+    #[error("Nonce {nonce} of {address} on {network} is reserved for time-locked transactions ([{start}, {end}))")]
+    NonceLaneReserved { network: String, address: String, nonce: u64, start: u64, end: u64 },
+    #[error("Signing intent {0} was resolved by reconciliation while in flight")]
+    Superseded(String),
+    #[error(
+        "An identical transaction ({}) was sent {age_secs}s ago and is still pending; \
+         set allow_duplicate to send it again",
+        tx_hash.as_deref().unwrap_or("not yet broadcast")
+    )]
+    DuplicateSuspected { tx_hash: Option<String>, age_secs: u64 },
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+    #[error("Signing failed: {0}")]
+    Signing(String),
+    #[error("Broadcast failed: {0}")]
+    Broadcast(String),
+    #[error(transparent)]
+    Chain(#[from] WalletError),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+impl IntentError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            IntentError::NonceInUse { .. } => "NONCE_IN_USE",
+            IntentError::NonceLaneReserved { .. } => "NONCE_LANE_RESERVED",
+            IntentError::Superseded(_) => "INTENT_SUPERSEDED",
+            IntentError::DuplicateSuspected { .. } => "DUPLICATE_TRANSACTION_SUSPECTED",
+            IntentError::InvalidTransaction(_) => "INVALID_TRANSACTION",
+            IntentError::Signing(_) => "SIGNING_FAILED",
+            IntentError::Broadcast(_) => "BROADCAST_FAILED",
+            IntentError::Chain(_) => "NETWORK_ERROR",
+            IntentError::Storage(_) => "DB_ERROR",
+        }
+    }
+}
+
+/// The RPC calls the critical section needs; abstracted so tests need no node.
+#[async_trait]
+pub trait BroadcastChain: Send + Sync {
+    /// Fills nonce (pending count of `from`, unless already set), gas and
+    /// chain id. Does not sign.
+    async fn prepare(&self, network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError>;
+
+    /// `eth_sendRawTransaction`
+    async fn send_raw_transaction(&self, network: &str, raw: Bytes) -> Result<H256, WalletError>;
+
+    /// Whether the node knows the transaction, pending or mined.
+    async fn transaction_known(&self, network: &str, tx_hash: H256) -> Result<bool, WalletError>;
+
+    /// Receipt once mined; `None` while pending or unknown.
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        _tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        Ok(None)
+    }
+
+    /// Nonce of the next transaction of `address`, counting pending ones.
+    async fn transaction_count(&self, network: &str, _address: Address) -> Result<u64, WalletError> {
+        Err(WalletError::NotImplemented(format!("transaction counts on {}", network)))
+    }
+
+    /// Height of the latest block (`eth_blockNumber`, or the last `newHeads`
+    /// notification on WebSocket endpoints).
+    async fn block_number(&self, network: &str) -> Result<u64, WalletError> {
+        Err(WalletError::NotImplemented(format!("block tracking on {}", network)))
+    }
+
+    /// Block `number` with its transaction hashes; `None` until it exists.
+    async fn block(&self, _network: &str, _number: u64) -> Result<Option<ChainBlock>, WalletError> {
+        Ok(None)
+    }
+}
+
+/// What block tracking needs of one block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainBlock {
+    pub number: u64,
+    pub hash: H256,
+    pub parent_hash: H256,
+    pub transactions: Vec<H256>,
+}
+
+/// Fee speed recorded for managed sends: the node fills gas price / caps
+pub const FEE_SPEED_NODE_SUGGESTED: &str = "node";
+
+/// SQLite INTEGER columns hold i64; larger values are not recorded
+fn sql_u64(value: Option<&U256>) -> Option<u64> {
+    value.filter(|v| **v <= U256::from(i64::MAX as u64)).map(U256::as_u64)
+}
+
+/// Native amount in wei; EVM networks without a known symbol still use 18
+/// decimals, tagged with the network name.
+fn native_amount(network: &str, wei: U256) -> Result<Amount, AmountError> {
+    match Amount::native_u256(network, wei) {
+        Err(AmountError::UnknownNetwork(_)) if wei <= U256::from(u128::MAX) => {
+            Ok(Amount::new(wei.as_u128(), 18, AssetTag::new(network, network.to_uppercase())))
+        }
+        other => other,
+    }
+}
+
+/// Outcome of reconciling one intent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    /// The transaction reached the network and is recorded
+    Recorded(String),
+    /// Nothing reached the network; the nonce is free again
+    Released,
+    /// Another caller moved the intent first
+    Unchanged,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ReconcileReport {
+    pub checked: usize,
+    pub recorded: usize,
+    pub released: usize,
+    pub errors: usize,
+}
+
+fn hex<T: std::fmt::Debug>(value: T) -> String {
+    format!("{:?}", value)
+}
+
+/// What makes two sends "the same" for duplicate detection.
+///
+/// `to` and `amount` are already parsed, so checksum casing and trailing
+/// zeros (`1.0` vs `1.000`) cannot make identical sends look different.
+#[derive(Debug, Clone, Copy)]
+pub struct SendFingerprint<'a> {
+    pub wallet_name: &'a str,
+    pub network: &'a str,
+    pub to: Address,
+    /// ERC-20 contract; `None` for the native asset
+    pub token: Option<Address>,
+    /// Minimal units (wei or token base units)
+    pub amount: U256,
+}
+
+pub struct SigningIntentLog {
+    storage: Arc<WalletStorage>,
+    chain: Arc<dyn BroadcastChain>,
+    duplicate_window_secs: u64,
+    metrics: Option<Arc<WalletMetrics>>,
+    tx_encoding: TxEncoding,
+}
+
+impl SigningIntentLog {
+    pub fn new(storage: Arc<WalletStorage>, chain: Arc<dyn BroadcastChain>) -> Self {
+        Self {
+            storage,
+            chain,
+            duplicate_window_secs: DEFAULT_DUPLICATE_WINDOW_SECS,
+            metrics: None,
+            tx_encoding: TxEncoding::default(),
+        }
+    }
+
+    pub fn with_tx_encoding(mut self, encoding: TxEncoding) -> Self {
+        self.tx_encoding = encoding;
+        self
+    }
+
+    /// 0 disables duplicate detection.
+    pub fn with_duplicate_window(mut self, secs: u64) -> Self {
+        self.duplicate_window_secs = secs;
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<WalletMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Window [`check_duplicate`](Self::check_duplicate) looks back over.
+    pub fn duplicate_window_secs(&self) -> u64 {
+        self.duplicate_window_secs
+    }
+
+    /// The RPC sends go through; confirmation polling reuses it.
+    pub fn chain(&self) -> Arc<dyn BroadcastChain> {
+        self.chain.clone()
+    }
+
+    /// Refuses `send` if an identical send is still in flight (an unresolved
+    /// intent) or `pending` in `transactions` and was created within the
+    /// window. `allow_duplicate` lets it through; both outcomes are counted.
+    pub async fn check_duplicate(
+        &self,
+        send: &SendFingerprint<'_>,
+        allow_duplicate: bool,
+    ) -> Result<(), IntentError> {
+        let Some((tx_hash, age_secs)) = self.find_duplicate(send).await? else {
+            return Ok(());
+        };
+        if let Some(metrics) = &self.metrics {
+            metrics.record_duplicate_send(allow_duplicate);
+        }
+        if allow_duplicate {
+            info!(
+                "wallet {}: sending duplicate of {} ({}s old) with allow_duplicate",
+                send.wallet_name,
+                tx_hash.as_deref().unwrap_or("an in-flight send"),
+                age_secs
+            );
+            return Ok(());
+        }
+        warn!("wallet {}: refused suspected duplicate send to {:?}", send.wallet_name, send.to);
+        Err(IntentError::DuplicateSuspected { tx_hash, age_secs })
+    }
+
+    /// Hash (if already signed) and age of the newest matching send.
+    async fn find_duplicate(
+        &self,
+        send: &SendFingerprint<'_>,
+    ) -> Result<Option<(Option<String>, u64)>, IntentError> {
+        // intents and `transactions` only hold native transfers, so a token
+        // send of the same amount never matches them
+        if self.duplicate_window_secs == 0 || send.token.is_some() {
+            return Ok(None);
+        }
+        let now = chrono::Utc::now();
+        let to = hex(send.to);
+
+        if let Some(intent) = self
+            .storage
+            .in_flight_signing_intent(
+                send.wallet_name,
+                send.network,
+                &to,
+                &send.amount.to_string(),
+                self.duplicate_window_secs,
+            )
+            .await?
+        {
+            let age_secs = (now.timestamp() - intent.created_at).max(0) as u64;
+            return Ok(Some((intent.tx_hash, age_secs)));
+        }
+
+        let since = now - chrono::Duration::seconds(self.duplicate_window_secs as i64);
+        let pending = self.storage.pending_transactions_since(send.wallet_name, send.network, since).await?;
+        Ok(pending
+            .into_iter()
+            .find(|tx| {
+                tx.to_address.eq_ignore_ascii_case(&to)
+                    && ethers::utils::parse_ether(&tx.amount).is_ok_and(|amount| amount == send.amount)
+            })
+            .map(|tx| (Some(tx.tx_hash), (now - tx.created_at).num_seconds().max(0) as u64)))
+    }
+
+    /// Prepares, signs, broadcasts and records `tx` from `signer`.
+    ///
+    /// Returns the transaction hash.
+    pub async fn send(
+        &self,
+        wallet_name: &str,
+        network: &str,
+        signer: &LocalWallet,
+        tx: TypedTransaction,
+    ) -> Result<String, IntentError> {
+        self.send_with_decision(wallet_name, network, signer, tx, None).await
+    }
+
+    /// [`send`](Self::send), recording `decision` (completed with the fee
+    /// fields the node filled in) alongside the transaction.
+    pub async fn send_with_decision(
+        &self,
+        wallet_name: &str,
+        network: &str,
+        signer: &LocalWallet,
+        mut tx: TypedTransaction,
+        decision: Option<DecisionContext>,
+    ) -> Result<String, IntentError> {
+        tx.set_from(signer.address());
+        self.chain.prepare(network, &mut tx).await?;
+        self.check_nonce_lane(network, &tx).await?;
+
+        let decision = decision.map(|mut decision| {
+            decision.record_fee(&tx);
+            decision.to_stored()
+        });
+        let intent = self.begin_with_decision(wallet_name, network, &tx, decision.as_deref()).await?;
+        let raw = self.sign(&intent, signer, &tx).await?;
+        self.finish_send(network, &tx, &intent, raw).await
+    }
+
+    /// Sends `raw`, signed earlier, through the same intent steps as
+    /// [`send`](Self::send). `tx` is its decoded form; its nonce may lie in a
+    /// reserved lane.
+    pub async fn send_presigned(
+        &self,
+        wallet_name: &str,
+        network: &str,
+        tx: &TypedTransaction,
+        raw: Bytes,
+    ) -> Result<String, IntentError> {
+        let intent = self.begin(wallet_name, network, tx).await?;
+        let tx_hash = hex(H256::from(keccak256(&raw)));
+        if !self.storage.advance_signing_intent(&intent.id, INTENT_SIGNING, INTENT_SIGNED, Some(&tx_hash)).await? {
+            return Err(IntentError::Superseded(intent.id.clone()));
+        }
+        self.finish_send(network, tx, &intent, raw).await
+    }
+
+    /// Ordinary sends stay out of the nonce lanes reserved for time locks.
+    async fn check_nonce_lane(&self, network: &str, tx: &TypedTransaction) -> Result<(), IntentError> {
+        let (Some(from), Some(nonce)) = (tx.from(), tx.nonce()) else {
+            return Ok(());
+        };
+        let from = hex(*from);
+        let nonce = nonce.low_u64();
+        match self.storage.nonce_lane_covering(network, &from, nonce).await? {
+            Some(lane) => Err(IntentError::NonceLaneReserved {
+                network: network.to_string(),
+                address: from,
+                nonce,
+                start: lane.start_nonce as u64,
+                end: lane.end_nonce as u64,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Broadcasts a signed intent and does the bookkeeping of a sent transaction.
+    async fn finish_send(
+        &self,
+        network: &str,
+        tx: &TypedTransaction,
+        intent: &SigningIntentRecord,
+        raw: Bytes,
+    ) -> Result<String, IntentError> {
+        let tx_hash = match self.broadcast(intent, raw).await {
+            Ok(tx_hash) => tx_hash,
+            // an RPC error does not prove the node dropped the transaction
+            Err(e @ IntentError::Broadcast(_)) => match self.reconcile_id(&intent.id).await {
+                Ok(Resolution::Recorded(tx_hash)) => return Ok(tx_hash),
+                Ok(_) => return Err(e),
+                Err(re) => {
+                    warn!("intent {}: left unresolved after failed broadcast: {}", intent.id, re);
+                    return Err(e);
+                }
+            },
+            Err(e) => return Err(e),
+        };
+        self.record(&intent.id).await?;
+        self.record_fee(network, tx, &tx_hash).await;
+        self.record_sent_metrics(network, tx);
+        Ok(tx_hash)
+    }
+
+    /// Value and fee ceiling (gas limit × gas price or max fee) in exact
+    /// minimal units; only the histogram observation is approximate.
+    fn record_sent_metrics(&self, network: &str, tx: &TypedTransaction) {
+        let Some(metrics) = &self.metrics else { return };
+        let per_gas = match tx {
+            TypedTransaction::Eip1559(tx) => tx.max_fee_per_gas,
+            other => other.gas_price(),
+        };
+        let fee = tx.gas().zip(per_gas).and_then(|(gas, price)| gas.checked_mul(price)).unwrap_or_default();
+        match (native_amount(network, tx.value().copied().unwrap_or_default()), native_amount(network, fee)) {
+            (Ok(value), Ok(fee)) => metrics.record_transaction_sent(&value, &fee),
+            (Err(e), _) | (_, Err(e)) => warn!("send metrics for {} not recorded: {}", network, e),
+        }
+    }
+
+    /// Best effort: fee analytics must never fail a send that already went out.
+    async fn record_fee(&self, network: &str, tx: &TypedTransaction, tx_hash: &str) {
+        let (gas_price, max_fee_per_gas, max_priority_fee_per_gas) = match tx {
+            TypedTransaction::Eip1559(tx) => {
+                (None, sql_u64(tx.max_fee_per_gas.as_ref()), sql_u64(tx.max_priority_fee_per_gas.as_ref()))
+            }
+            other => (sql_u64(other.gas_price().as_ref()), None, None),
+        };
+        let record = NewFeeRecord {
+            network,
+            tx_hash,
+            gas_limit: sql_u64(tx.gas()).unwrap_or_default(),
+            gas_price,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            speed: Some(FEE_SPEED_NODE_SUGGESTED),
+        };
+        if let Err(e) = self.storage.record_fee_broadcast(&record).await {
+            warn!("fee history for {} not recorded: {}", tx_hash, e);
+        }
+    }
+
+    /// Step 1: commits a `signing` intent for the nonce `tx` carries.
+    pub async fn begin(
+        &self,
+        wallet_name: &str,
+        network: &str,
+        tx: &TypedTransaction,
+    ) -> Result<SigningIntentRecord, IntentError> {
+        self.begin_with_decision(wallet_name, network, tx, None).await
+    }
+
+    /// `decision` is the stored form of a [`DecisionContext`].
+    async fn begin_with_decision(
+        &self,
+        wallet_name: &str,
+        network: &str,
+        tx: &TypedTransaction,
+        decision: Option<&str>,
+    ) -> Result<SigningIntentRecord, IntentError> {
+        let invalid = |what: &str| IntentError::InvalidTransaction(format!("{} is not set", what));
+        let from = tx.from().copied().ok_or_else(|| invalid("from"))?;
+        let to = tx.to_addr().copied().ok_or_else(|| invalid("to"))?;
+        let nonce = tx.nonce().copied().ok_or_else(|| invalid("nonce"))?;
+        if tx.chain_id().is_none() {
+            return Err(invalid("chain_id"));
+        }
+        if nonce > U256::from(i64::MAX as u64) {
+            return Err(IntentError::InvalidTransaction(format!("nonce {} out of range", nonce)));
+        }
+        // the record written after broadcast needs the stored wallet; refuse before signing
+        if self.storage.wallet_id(wallet_name).await?.is_none() {
+            return Err(IntentError::Storage(anyhow::anyhow!("Wallet not found: {}", wallet_name)));
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let from = hex(from);
+        let begun = self
+            .storage
+            .begin_signing_intent(&NewSigningIntent {
+                id: &id,
+                wallet_name,
+                network,
+                from_address: &from,
+                to_address: &hex(to),
+                value: &tx.value().copied().unwrap_or_default().to_string(),
+                nonce: nonce.as_u64() as i64,
+                canonical_hash: &hex(tx.sighash()),
+                decision,
+            })
+            .await?;
+        if !begun {
+            return Err(IntentError::NonceInUse {
+                network: network.to_string(),
+                address: from,
+                nonce: nonce.as_u64(),
+            });
+        }
+        self.storage
+            .get_signing_intent(&id)
+            .await?
+            .ok_or_else(|| IntentError::Storage(anyhow::anyhow!("signing intent {} vanished", id)))
+    }
+
+    /// Step 2: signs and commits the signed hash before anything is sent.
+    pub async fn sign(
+        &self,
+        intent: &SigningIntentRecord,
+        signer: &LocalWallet,
+        tx: &TypedTransaction,
+    ) -> Result<Bytes, IntentError> {
+        if hex(tx.sighash()) != intent.canonical_hash || hex(signer.address()) != intent.from_address {
+            self.storage.release_signing_intent(&intent.id, INTENT_SIGNING, "transaction changed").await?;
+            return Err(IntentError::InvalidTransaction(
+                "transaction does not match its signing intent".to_string(),
+            ));
+        }
+        let raw = match sign_transaction(self.tx_encoding, signer, tx) {
+            Ok(raw) => raw,
+            Err(e) => {
+                self.storage.release_signing_intent(&intent.id, INTENT_SIGNING, "signing failed").await?;
+                return Err(IntentError::Signing(e.to_string()));
+            }
+        };
+        let tx_hash = hex(H256::from(keccak256(&raw)));
+        if !self
+            .storage
+            .advance_signing_intent(&intent.id, INTENT_SIGNING, INTENT_SIGNED, Some(&tx_hash))
+            .await?
+        {
+            return Err(IntentError::Superseded(intent.id.clone()));
+        }
+        Ok(raw)
+    }
+
+    /// Step 3: sends the signed bytes and commits `broadcast`.
+    pub async fn broadcast(&self, intent: &SigningIntentRecord, raw: Bytes) -> Result<String, IntentError> {
+        let tx_hash = self
+            .chain
+            .send_raw_transaction(&intent.network, raw)
+            .await
+            .map_err(|e| IntentError::Broadcast(e.to_string()))?;
+        let tx_hash = hex(tx_hash);
+        if !self
+            .storage
+            .advance_signing_intent(&intent.id, INTENT_SIGNED, INTENT_BROADCAST, Some(&tx_hash))
+            .await?
+        {
+            warn!("intent {}: broadcast {} after the intent was resolved", intent.id, tx_hash);
+            return Err(IntentError::Superseded(intent.id.clone()));
+        }
+        info!("intent {}: broadcast {} (nonce {})", intent.id, tx_hash, intent.nonce);
+        Ok(tx_hash)
+    }
+
+    /// Step 4: writes the transaction record and commits `recorded`.
+    pub async fn record(&self, id: &str) -> Result<(), IntentError> {
+        let intent = self
+            .storage
+            .get_signing_intent(id)
+            .await?
+            .ok_or_else(|| IntentError::Storage(anyhow::anyhow!("signing intent {} not found", id)))?;
+        if intent.state == INTENT_RECORDED {
+            return Ok(());
+        }
+        self.complete(&intent).await.map(|_| ())
+    }
+
+    /// The record's id is the intent id, so finishing twice writes one row.
+    /// The intent's decision context is written in the same transaction.
+    async fn complete(&self, intent: &SigningIntentRecord) -> Result<Resolution, IntentError> {
+        let tx_hash = intent
+            .tx_hash
+            .clone()
+            .ok_or_else(|| IntentError::InvalidTransaction(format!("intent {} has no hash", intent.id)))?;
+        if !self.storage.intent_transaction_recorded(&intent.id).await? {
+            let wallet_id = self.storage.wallet_id(&intent.wallet_name).await?.ok_or_else(|| {
+                IntentError::Storage(anyhow::anyhow!("Wallet not found: {}", intent.wallet_name))
+            })?;
+            let value = U256::from_dec_str(&intent.value).unwrap_or_default();
+            let record = TransactionRecord {
+                id: intent.id.clone(),
+                wallet_id,
+                tx_hash: tx_hash.clone(),
+                network: intent.network.clone(),
+                from_address: intent.from_address.clone(),
+                to_address: intent.to_address.clone(),
+                amount: ethers::utils::format_ether(value),
+                fee: "0".to_string(),
+                status: "pending".to_string(),
+                created_at: chrono::Utc::now(),
+                confirmed_at: None,
+                integrity_hash: String::new(),
+                fee_actual: None,
+                block_number: None,
+            };
+            self.storage.store_transaction_with_decision(&record, intent.decision.as_deref()).await?;
+        }
+        if !self.storage.advance_signing_intent(&intent.id, &intent.state, INTENT_RECORDED, None).await? {
+            return Ok(Resolution::Unchanged);
+        }
+        Ok(Resolution::Recorded(tx_hash))
+    }
+
+    async fn reconcile_id(&self, id: &str) -> Result<Resolution, IntentError> {
+        match self.storage.get_signing_intent(id).await? {
+            Some(intent) => self.reconcile_intent(&intent).await,
+            None => Ok(Resolution::Unchanged),
+        }
+    }
+
+    /// Drives one intent to a terminal state without re-broadcasting.
+    pub async fn reconcile_intent(&self, intent: &SigningIntentRecord) -> Result<Resolution, IntentError> {
+        let release = |reason: &'static str| async move {
+            let released = self.storage.release_signing_intent(&intent.id, &intent.state, reason).await?;
+            Ok::<_, IntentError>(if released { Resolution::Released } else { Resolution::Unchanged })
+        };
+        match intent.state.as_str() {
+            INTENT_SIGNING => release("interrupted before signing completed").await,
+            INTENT_SIGNED => {
+                let tx_hash: H256 = intent
+                    .tx_hash
+                    .as_deref()
+                    .unwrap_or_default()
+                    .parse()
+                    .map_err(|_| IntentError::InvalidTransaction(format!("intent {} has no hash", intent.id)))?;
+                // the signed bytes were never persisted: an unknown hash cannot appear later
+                // from this process, so the nonce is safe to hand out again
+                if self.chain.transaction_known(&intent.network, tx_hash).await? {
+                    self.complete(intent).await
+                } else {
+                    release("signed transaction never reached the network").await
+                }
+            }
+            INTENT_BROADCAST => self.complete(intent).await,
+            INTENT_RECORDED | INTENT_RELEASED => Ok(Resolution::Unchanged),
+            other => Err(IntentError::Storage(anyhow::anyhow!(
+                "signing intent {} has unknown state {}",
+                intent.id,
+                other
+            ))),
+        }
+    }
+
+    /// Reconciles unresolved intents untouched for `min_age_secs`; `0`
+    /// includes everything, which is only safe when nothing is in flight
+    /// (startup).
+    pub async fn reconcile(&self, min_age_secs: u64) -> Result<ReconcileReport, IntentError> {
+        let intents = self.storage.unresolved_signing_intents(min_age_secs, MAX_RECONCILE_BATCH).await?;
+        let mut report = ReconcileReport { checked: intents.len(), ..Default::default() };
+        for intent in &intents {
+            match self.reconcile_intent(intent).await {
+                Ok(Resolution::Recorded(tx_hash)) => {
+                    info!("intent {}: recorded {} during reconciliation", intent.id, tx_hash);
+                    report.recorded += 1;
+                }
+                Ok(Resolution::Released) => {
+                    info!(
+                        "intent {}: released nonce {} of {} on {}",
+                        intent.id, intent.nonce, intent.from_address, intent.network
+                    );
+                    report.released += 1;
+                }
+                Ok(Resolution::Unchanged) => {}
+                Err(e) => {
+                    warn!("intent {}: reconciliation failed: {}", intent.id, e);
+                    report.errors += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+}
@@ -12,6 +12,8 @@
 
 pub mod api;
 pub mod blockchain;
+// Build metadata and runtime version reporting
+pub mod build_info;
 pub mod cli;
 pub mod core;
 pub mod crypto;
@@ -19,6 +21,8 @@ pub mod security;
 pub mod shamir;
 pub mod storage;
 pub mod tools;
+// Shared helpers (retry policy)
+pub mod util;
 
 // Hardware wallet support modules
 #[cfg(any(feature = "trezor", feature = "ledger"))]
@@ -36,6 +40,24 @@ pub mod monitoring;
 pub mod mvp;
 pub mod network;
 pub mod ops;
+// EIP-2771 meta-transaction relay
+pub mod relay;
+// ERC-4337 account abstraction (smart account owner key)
+pub mod erc4337;
+// Write-ahead log around server-side signing
+pub mod intents;
+// Four-eyes approval of sends above a review threshold
+pub mod approvals;
+// Multi-step operation bundles with resumable execution
+pub mod operations;
+// Runtime feature flags with per-wallet rules and percentage rollouts
+pub mod feature_flags;
+// Behavior flags of non-standard ERC-20 tokens
+pub mod token_registry;
+// Transactions signed now and broadcast once their unlock conditions hold
+pub mod timelocks;
+// Fiat prices for balance and history display
+pub mod pricing;
 // Add this export so tests can use `defi_hot_wallet::audit::...`
 pub mod audit;
 pub mod service;
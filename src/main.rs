@@ -4,10 +4,23 @@
 use anyhow::Result;
 use clap::{Args as ClapArgs, Parser, Subcommand};
 use defi_hot_wallet::api::server::WalletServer;
-use defi_hot_wallet::core::config::{BlockchainConfig, StorageConfig, WalletConfig};
+use defi_hot_wallet::core::config::{
+    BlockchainConfig, BridgeBackend, ConfigSecretsConfig, PreflightConfig, SecretBackendConfig, SecretSourceConfig,
+    SecurityConfig, StorageConfig, WalletConfig,
+};
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::cli::client::{self, ClientArgs};
+use defi_hot_wallet::core::wallet_manager::WalletManager;
+use defi_hot_wallet::ops::preflight::Preflight;
+use defi_hot_wallet::ops::sandbox_clone::{CloneReport, SandboxClone};
+use defi_hot_wallet::security::env_manager::config_secrets;
+use defi_hot_wallet::security::env_manager::secret_backend::{self, SecretBackend, SecretStore};
+use defi_hot_wallet::storage::{open_backend, CloneSource, StorageBackend, WalSettings};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::info;
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
@@ -18,6 +31,10 @@ use tracing_subscriber::{EnvFilter, FmtSubscriber};
 struct Args {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Permit mock backends (e.g. BRIDGE_BACKEND=mock) in builds without `test-env`
+    #[arg(long, global = true)]
+    allow_insecure_mocks: bool,
 }
 
 #[derive(Subcommand)]
@@ -27,9 +44,31 @@ enum Commands {
         /// Port to bind the server to
         #[arg(long, default_value = "8888")]
         port: u16,
+        /// Run only the startup dependency preflight, print the report and exit
+        #[arg(long)]
+        check: bool,
     },
     /// Create a wallet file with the provided name at the given path
     Create(CreateArgs),
+    /// Talk to a running server: list, create, balance, send
+    Client(ClientArgs),
+    /// Clone wallets into a sandbox database with freshly generated keys and
+    /// print the report. New wallets get the password in SANDBOX_WALLET_PASSWORD.
+    SandboxClone(SandboxCloneArgs),
+    /// Maintain config.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Replace secrets written inline in the config file with ${secure:NAME}
+    /// references. The values go to the file secret backend when one is
+    /// configured, otherwise to 0600 files in a new directory; only their
+    /// names and the commands to store them are printed.
+    MigrateSecrets(MigrateSecretsArgs),
 }
 
 #[derive(ClapArgs)]
@@ -42,14 +81,46 @@ struct CreateArgs {
     output: PathBuf,
 }
 
+#[derive(ClapArgs)]
+struct SandboxCloneArgs {
+    /// Database to clone from; opened read-only
+    #[arg(long)]
+    source_db: String,
+    /// Sandbox database to clone into; created when missing
+    #[arg(long)]
+    target_db: String,
+    /// Comma-separated wallet names (default: every wallet)
+    #[arg(long, value_delimiter = ',')]
+    wallets: Option<Vec<String>>,
+}
+
+#[derive(ClapArgs)]
+struct MigrateSecretsArgs {
+    /// Config file to rewrite (default: CONFIG_PATH or config.toml)
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Directory for the extracted values when the backend is not `file`
+    /// (default: a new directory under the system temp dir)
+    #[arg(long)]
+    secrets_dir: Option<PathBuf>,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    defi_hot_wallet::build_info::mark_process_start();
+    defi_hot_wallet::ops::incidents::install_panic_hook();
     let args = Args::parse();
 
+    // The client prints its result on stdout; run it before server logging is set up
+    if let Some(Commands::Client(client_args)) = args.command {
+        std::process::exit(client::execute(client_args).await);
+    }
+
     // Initialize logging
     init_logging()?;
 
-    info!("Starting DeFi Hot Wallet v{}", env!("CARGO_PKG_VERSION"));
+    let build = defi_hot_wallet::build_info::BuildInfo::current();
+    info!("Starting DeFi Hot Wallet v{} ({}, {})", build.version, build.describe(), build.build_timestamp);
 
     // Runtime safety: refuse to run in production if TEST_SKIP_DECRYPT is set.
     if std::env::var("TEST_SKIP_DECRYPT").is_ok() && !cfg!(feature = "test-env") {
@@ -81,21 +152,65 @@ async fn main() -> Result<()> {
         create_wallet_file(&create_args.name, &create_args.output)?;
         return Ok(());
     }
-
-    // Validate all environment variables before use
-    #[cfg(not(any(test, feature = "test-env")))]
-    {
-        use defi_hot_wallet::security::env_validator::EnvValidator;
-        EnvValidator::validate_all()
-            .map_err(|e| anyhow::anyhow!("Environment variable validation failed: {}", e))?;
+    if let Some(Commands::Config { command: ConfigCommand::MigrateSecrets(migrate_args) }) = &args.command {
+        let path = migrate_args.config.clone().unwrap_or_else(|| PathBuf::from(config_path()));
+        let backend = load_security_section::<SecretBackendConfig>("secret_backend")?;
+        config_secrets::migrate(&path, &backend.source, migrate_args.secrets_dir.as_deref(), &mut std::io::stdout())?;
+        return Ok(());
     }
 
     // Use default database path (or read from DATABASE_URL env var if available)
     let database_url = std::env::var("DATABASE_URL")
         .unwrap_or_else(|_| "sqlite://./wallets.db".to_string());
+    // Optional read replica for history/listing queries
+    let read_database_url = std::env::var("READ_DATABASE_URL").ok().filter(|url| !url.is_empty());
+
+    // Secrets from a backend other than the environment are read once here;
+    // without the master key the server does not start.
+    let sections = load_security_section::<SecretBackendConfig>("secret_backend")
+        .and_then(|backend| Ok((backend, load_security_section::<ConfigSecretsConfig>("config_secrets")?)));
+    let (secret_backend_config, config_secrets_config) = match sections {
+        Ok(sections) => sections,
+        Err(e) => {
+            tracing::error!("Refusing to start: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let backend = match secret_backend::backend_from_config(&secret_backend_config.source) {
+        Ok(backend) => backend,
+        Err(e) => {
+            tracing::error!("Refusing to start: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if secret_backend_config.source != SecretSourceConfig::Env {
+        let max_staleness = Some(secret_backend_config.max_staleness_secs)
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs);
+        match SecretStore::open(backend.clone(), max_staleness).await {
+            Ok(store) => {
+                secret_backend::install(Arc::new(store));
+            }
+            Err(e) => {
+                tracing::error!("Refusing to start: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // config.toml with its ${secure:NAME} references resolved; every section
+    // below is read from this document
+    let config_doc = match load_config_document(&config_secrets_config, backend.as_ref()).await {
+        Ok(doc) => doc,
+        Err(e) => {
+            tracing::error!("Refusing to start: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let config_doc = config_doc.as_ref();
 
     // Load blockchain network configuration from config.toml or use defaults
-    let blockchain_config = load_blockchain_config().unwrap_or_else(|e| {
+    let blockchain_config = load_blockchain_config(config_doc).unwrap_or_else(|e| {
         tracing::warn!("Failed to load config.toml: {}. Using default configuration", e);
         create_default_blockchain_config()
     });
@@ -106,19 +221,107 @@ async fn main() -> Result<()> {
             database_url: database_url.clone(),
             max_connections: Some(10),
             connection_timeout_seconds: Some(30),
+            read_database_url,
         },
         blockchain: blockchain_config,
         quantum_safe: false,
         multi_sig_threshold: 2,
         derivation: Default::default(),
-        security: defi_hot_wallet::core::config::SecurityConfig::default(),
+        // [security.secret_backend] / [security.config_secrets] were read above,
+        // strictly and before references were resolved; they override the copy here
+        security: SecurityConfig {
+            secret_backend: secret_backend_config,
+            config_secrets: config_secrets_config,
+            ..load_config_section(config_doc, "security")
+        },
+        bridge_backend: load_bridge_backend()?,
+        balance_snapshots: load_config_section(config_doc, "balance_snapshots"),
+        backups: load_config_section(config_doc, "backups"),
+        relay: load_config_section(config_doc, "relay"),
+        cluster: load_config_section(config_doc, "cluster"),
+        account_abstraction: load_config_section(config_doc, "account_abstraction"),
+        fee_tracking: load_config_section(config_doc, "fee_tracking"),
+        approvals: load_config_section(config_doc, "approvals"),
+        pricing: load_config_section(config_doc, "pricing"),
+        jobs: load_config_section(config_doc, "jobs"),
+        wal: load_config_section(config_doc, "wal"),
+        database_queries: load_config_section(config_doc, "database_queries"),
+        recipient_guard: load_config_section(config_doc, "recipient_guard"),
+        reserve_reports: load_config_section(config_doc, "reserve_reports"),
+        admission: load_config_section(config_doc, "admission"),
+        backfill: load_config_section(config_doc, "backfill"),
+        feature_flags: load_config_section(config_doc, "feature_flags"),
+        tokens: load_config_section(config_doc, "tokens"),
+        timelocks: load_config_section(config_doc, "timelocks"),
+        bridge_poller: load_config_section(config_doc, "bridge_poller"),
+        anomaly_detection: load_config_section(config_doc, "anomaly_detection"),
     };
 
-    // Read API_KEY from environment securely
-    let api_key = defi_hot_wallet::security::env_manager::secure_env::get_api_key().ok();
+    // sandbox-clone writes its target database and exits without starting the server
+    if let Some(Commands::SandboxClone(clone_args)) = &args.command {
+        let report = run_sandbox_clone(clone_args, &wallet_config).await?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    // Dependency preflight: required failures abort with exit code 2 before
+    // anything binds. `server --check` stops here either way.
+    let check_only = matches!(args.command, Some(Commands::Server { check: true, .. }));
+    let report = Preflight::new(&wallet_config, &load_config_section::<PreflightConfig>(config_doc, "preflight"))
+        .run()
+        .await;
+    info!(target: "preflight", report = %report.to_json(), passed = report.passed, "startup preflight finished");
+    if check_only {
+        print!("{}", report.to_table());
+        std::process::exit(report.exit_code());
+    }
+    if !report.passed {
+        eprint!("{}", report.to_table());
+        for failure in report.failures() {
+            tracing::error!("Refusing to start: preflight check {} failed: {}", failure.name, failure.detail);
+        }
+        std::process::exit(report.exit_code());
+    }
+
+    // Validate all environment variables before use
+    #[cfg(not(any(test, feature = "test-env")))]
+    {
+        use defi_hot_wallet::security::env_validator::EnvValidator;
+        EnvValidator::validate_all()
+            .map_err(|e| anyhow::anyhow!("Environment variable validation failed: {}", e))?;
+    }
+
+    // Read API_KEY (and API_KEY_SECONDARY during a rotation overlap) securely
+    let api_keys = defi_hot_wallet::security::SECURE_ENV_MANAGER.get_api_keys()?;
+    let api_key = api_keys.as_ref().map(|keys| keys.primary.clone());
 
-    let server =
-        WalletServer::new("0.0.0.0".to_string(), 8888, wallet_config.clone(), api_key).await?;
+    // Refuse to start with a mock bridge backend unless this is a test-env build
+    // or the operator explicitly passed --allow-insecure-mocks.
+    let server = match WalletServer::new_with_mock_policy(
+        "0.0.0.0".to_string(),
+        8888,
+        wallet_config.clone(),
+        api_key,
+        args.allow_insecure_mocks,
+    )
+    .await
+    {
+        Ok(server) => server,
+        Err(defi_hot_wallet::core::errors::WalletError::ConfigError(msg)) => {
+            tracing::error!("Refusing to start: {}", msg);
+            std::process::exit(1);
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let server = match api_keys.and_then(|keys| keys.secondary) {
+        Some(secondary) => server.with_secondary_api_key(secondary),
+        None => server,
+    };
+    if server.sandbox {
+        tracing::warn!("==================================================================");
+        tracing::warn!(" SANDBOX DATABASE: cloned wallets with sandbox keys, mainnets disabled");
+        tracing::warn!("==================================================================");
+    }
 
     // Initialize global encryption consistency validator
     let quantum_crypto = if wallet_config.quantum_safe {
@@ -129,7 +332,7 @@ async fn main() -> Result<()> {
     defi_hot_wallet::crypto::init_global_validator(quantum_crypto)?;
 
     match args.command {
-        Some(Commands::Server { port }) => {
+        Some(Commands::Server { port, .. }) => {
             info!("Starting server on port {}", port);
             let server_with_port = WalletServer { port, ..server };
             server_with_port.start().await?;
@@ -139,8 +342,10 @@ async fn main() -> Result<()> {
             info!("No command specified, starting server on default port 8888");
             server.start().await?;
         }
-        // Create handled above
-        Some(Commands::Create(_)) => unreachable!(),
+        // Create, Client, SandboxClone and Config handled above
+        Some(Commands::Create(_) | Commands::Client(_) | Commands::SandboxClone(_) | Commands::Config { .. }) => {
+            unreachable!()
+        }
     }
 
     Ok(())
@@ -159,13 +364,111 @@ fn init_logging() -> Result<()> {
     Ok(())
 }
 
+/// `sandbox-clone`: wallets are created with the server's configuration
+/// minus the mainnets, which is what a server started on the target loads.
+async fn run_sandbox_clone(args: &SandboxCloneArgs, base: &WalletConfig) -> Result<CloneReport> {
+    let password = std::env::var("SANDBOX_WALLET_PASSWORD")
+        .map_err(|_| anyhow::anyhow!("SANDBOX_WALLET_PASSWORD must hold the password for the sandbox wallets"))?;
+    let mut config = base.clone();
+    config.storage.database_url = args.target_db.clone();
+    config.storage.read_database_url = None;
+    config.blockchain.remove_mainnets();
+
+    let source = CloneSource::open(&args.source_db).await?;
+    // only infallible without the postgres feature
+    #[allow(clippy::infallible_destructuring_match)]
+    let target = match open_backend(&args.target_db, WalSettings::default()).await? {
+        StorageBackend::Sqlite(target) => target,
+        #[cfg(feature = "postgres")]
+        StorageBackend::Postgres(_) => anyhow::bail!("sandbox clones are SQLite databases; use a sqlite: target"),
+    };
+    let manager = WalletManager::new(&config).await?;
+    let clients = ClientRegistry::from_config(&config.blockchain);
+    let mut clone = SandboxClone::new(password);
+    if let Some(wallets) = &args.wallets {
+        clone = clone.with_wallets(wallets.clone());
+    }
+    let report = clone.run(&source, &target, &manager, &clients).await;
+    source.close().await;
+    Ok(report?)
+}
+
+/// Requested bridge backend from BRIDGE_BACKEND (default: real).
+/// The legacy BRIDGE_MOCK* variables are no longer honoured by the bridge module.
+fn load_bridge_backend() -> Result<BridgeBackend> {
+    for legacy in ["BRIDGE_MOCK", "BRIDGE_MOCK_FORCE_SUCCESS", "FORCE_BRIDGE_SUCCESS", "BRIDGE_MOCK_FORCE"] {
+        if std::env::var(legacy).is_ok() {
+            tracing::warn!("{} is ignored; use BRIDGE_BACKEND=mock with --allow-insecure-mocks", legacy);
+        }
+    }
+    match std::env::var("BRIDGE_BACKEND") {
+        Ok(v) => Ok(v.parse::<BridgeBackend>()?),
+        Err(_) => Ok(BridgeBackend::default()),
+    }
+}
+
+fn config_path() -> String {
+    std::env::var("CONFIG_PATH").unwrap_or_else(|_| "config.toml".to_string())
+}
+
+/// config.toml with inline secrets checked against `policy` and references
+/// resolved through `backend`; `None` when the file is missing or not TOML
+/// (every section then uses its defaults).
+async fn load_config_document(
+    policy: &ConfigSecretsConfig,
+    backend: &dyn SecretBackend,
+) -> Result<Option<toml::Value>, config_secrets::ConfigSecretError> {
+    let path = config_path();
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    match config_secrets::load(&content, policy.inline, backend).await {
+        Ok(doc) => Ok(Some(doc)),
+        Err(config_secrets::ConfigSecretError::Parse(e)) => {
+            tracing::warn!("Failed to parse {}: {}. Using default configuration", path, e);
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// A top-level table (`[backups]`, `[relay]`) from config.toml; defaults
+/// (disabled) when absent or invalid.
+fn load_config_section<T: serde::de::DeserializeOwned + Default>(config: Option<&toml::Value>, section: &str) -> T {
+    let Some(table) = config.and_then(|config| config.get(section)) else {
+        return T::default();
+    };
+    table.clone().try_into::<T>().unwrap_or_else(|e| {
+        tracing::warn!("Invalid [{}] section in {}: {}. Using defaults (disabled)", section, config_path(), e);
+        T::default()
+    })
+}
+
+/// `[security.<section>]` read from the file as written, before references
+/// are resolved: these sections say how to resolve them. Unlike the sections
+/// above an invalid one is an error: falling back to the defaults would
+/// silently ignore the secret backend or the strict inline-secret policy.
+fn load_security_section<T: serde::de::DeserializeOwned + Default>(section: &str) -> Result<T> {
+    let config_path = config_path();
+    let Ok(content) = fs::read_to_string(&config_path) else {
+        return Ok(T::default());
+    };
+    let config: toml::Value = toml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", config_path, e.message()))?;
+    match config.get("security").and_then(|security| security.get(section)) {
+        Some(table) => table
+            .clone()
+            .try_into()
+            .map_err(|e| anyhow::anyhow!("Invalid [security.{}] section in {}: {}", section, config_path, e)),
+        None => Ok(T::default()),
+    }
+}
+
 /// Load blockchain configuration from config.toml
-fn load_blockchain_config() -> Result<BlockchainConfig> {
-    use defi_hot_wallet::core::config::NetworkConfig;
+fn load_blockchain_config(config: Option<&toml::Value>) -> Result<BlockchainConfig> {
+    use defi_hot_wallet::core::config::{ExplorerUrlTemplate, NetworkConfig, RpcEndpointConfig, RpcRateLimit};
     
-    let config_path = std::env::var("CONFIG_PATH").unwrap_or_else(|_| "config.toml".to_string());
-    let config_content = fs::read_to_string(&config_path)?;
-    let config: toml::Value = toml::from_str(&config_content)?;
+    let config = config.ok_or_else(|| anyhow::anyhow!("{} is missing or invalid", config_path()))?;
     
     let mut networks = HashMap::new();
     
@@ -183,6 +486,41 @@ fn load_blockchain_config() -> Result<BlockchainConfig> {
                         .map(|v| v as u64)
                         .unwrap_or(1);
                     
+                    // [blockchain.networks.<name>.explorer_url_template] tx / address;
+                    // placeholders are validated when the server starts
+                    let explorer_url_template =
+                        network_table.get("explorer_url_template").map(|template| {
+                            let field = |key: &str| {
+                                template.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string()
+                            };
+                            ExplorerUrlTemplate { tx: field("tx"), address: field("address") }
+                        });
+                    // the pre-template `explorer_url = "<base>"` still yields Etherscan-style links
+                    let explorer_url_template = explorer_url_template.or_else(|| {
+                        let base = network_table.get("explorer_url")?.as_str()?;
+                        tracing::warn!(
+                            "blockchain.networks.{}.explorer_url is deprecated; use explorer_url_template",
+                            name
+                        );
+                        Some(ExplorerUrlTemplate::etherscan_style(base))
+                    });
+
+                    // rate_limit / fallback_endpoints keep their serde shape; zero limits are
+                    // rejected when the server starts
+                    let rate_limit = network_table
+                        .get("rate_limit")
+                        .map(|v| v.clone().try_into::<RpcRateLimit>())
+                        .transpose()?;
+                    let fallback_endpoints = network_table
+                        .get("fallback_endpoints")
+                        .map(|v| v.clone().try_into::<Vec<RpcEndpointConfig>>())
+                        .transpose()?
+                        .unwrap_or_default();
+                    let pending_expiry_seconds = network_table
+                        .get("pending_expiry_seconds")
+                        .and_then(|v| v.as_integer())
+                        .map(|v| v.max(0) as u64);
+
                     // Get network name from config
                     let network_name = network_table.get("name")
                         .and_then(|v| v.as_str())
@@ -193,10 +531,15 @@ fn load_blockchain_config() -> Result<BlockchainConfig> {
                         name: network_name,
                         rpc_url,
                         chain_id,
+                        explorer_url_template,
+                        rate_limit,
+                        fallback_endpoints,
+                        pending_expiry_seconds,
                     });
                     
                     if let Some(network) = networks.get(name) {
-                        info!("Loaded network config: {} (RPC: {})", name, network.rpc_url);
+                        let rpc_url = config_secrets::redact_url(&network.rpc_url);
+                        info!("Loaded network config: {} (RPC: {})", name, rpc_url);
                     }
                 }
             }
@@ -210,7 +553,7 @@ fn load_blockchain_config() -> Result<BlockchainConfig> {
 
 /// Create default blockchain configuration for testnet
 fn create_default_blockchain_config() -> BlockchainConfig {
-    use defi_hot_wallet::core::config::NetworkConfig;
+    use defi_hot_wallet::core::config::{ExplorerUrlTemplate, NetworkConfig};
     
     let mut networks = HashMap::new();
     
@@ -219,6 +562,10 @@ fn create_default_blockchain_config() -> BlockchainConfig {
         name: "Ethereum Mainnet".to_string(),
         rpc_url: "https://eth.llamarpc.com".to_string(),
         chain_id: 1,
+        explorer_url_template: Some(ExplorerUrlTemplate::etherscan_style("https://etherscan.io")),
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     // Ethereum Sepolia Testnet
@@ -226,6 +573,10 @@ fn create_default_blockchain_config() -> BlockchainConfig {
         name: "Sepolia Testnet".to_string(),
         rpc_url: "https://sepolia.drpc.org".to_string(),
         chain_id: 11155111,
+        explorer_url_template: Some(ExplorerUrlTemplate::etherscan_style("https://sepolia.etherscan.io")),
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     // Polygon
@@ -233,6 +584,10 @@ fn create_default_blockchain_config() -> BlockchainConfig {
         name: "Polygon Mainnet".to_string(),
         rpc_url: "https://polygon-rpc.com".to_string(),
         chain_id: 137,
+        explorer_url_template: Some(ExplorerUrlTemplate::etherscan_style("https://polygonscan.com")),
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     // BSC
@@ -240,6 +595,10 @@ fn create_default_blockchain_config() -> BlockchainConfig {
         name: "BSC Mainnet".to_string(),
         rpc_url: "https://bsc-dataseed.binance.org".to_string(),
         chain_id: 56,
+        explorer_url_template: Some(ExplorerUrlTemplate::etherscan_style("https://bscscan.com")),
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     // BSC Testnet
@@ -247,6 +606,10 @@ fn create_default_blockchain_config() -> BlockchainConfig {
         name: "BSC Testnet".to_string(),
         rpc_url: "https://data-seed-prebsc-1-s1.binance.org:8545".to_string(),
         chain_id: 97,
+        explorer_url_template: Some(ExplorerUrlTemplate::etherscan_style("https://testnet.bscscan.com")),
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     
@@ -6,6 +6,8 @@ use prometheus::{Counter, Histogram, Registry, Encoder, TextEncoder};
 use std::sync::Arc;
 use parking_lot::Mutex;
 
+use crate::core::amount::Amount;
+
 /// 业务指标管理器
 pub struct BusinessMetrics {
     registry: Arc<Mutex<Registry>>,
@@ -155,18 +157,18 @@ impl BusinessMetrics {
         self.wallets_deleted_total.inc();
     }
     
-    /// 记录transaction
+    /// 记录transaction（直方图观测为主单位的近似值）
     pub fn record_transaction(
         &self,
-        value_eth: f64,
-        fee_eth: f64,
+        value: &Amount,
+        fee: &Amount,
         duration_ms: u64,
         success: bool,
     ) {
         if success {
             self.transactions_sent_total.inc();
-            self.transaction_value_eth.observe(value_eth);
-            self.transaction_fees_eth.observe(fee_eth);
+            self.transaction_value_eth.observe(value.to_f64_lossy());
+            self.transaction_fees_eth.observe(fee.to_f64_lossy());
         } else {
             self.transactions_failed_total.inc();
         }
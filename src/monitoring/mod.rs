@@ -1,8 +1,12 @@
 use anyhow::Result;
-use prometheus::{Counter, Encoder, Gauge, Histogram, HistogramOpts, Registry, TextEncoder};
+use prometheus::{
+    Counter, Encoder, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry,
+    TextEncoder,
+};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::{error, info, warn};
+use crate::core::amount::Amount;
 use crate::security::redaction::redact_body;
 
 pub struct WalletMetrics {
@@ -18,22 +22,79 @@ pub struct WalletMetrics {
     pub transactions_failed: Counter,
     pub transaction_value: Histogram,
     pub transaction_fees: Histogram,
+    pub duplicate_sends_rejected: Counter,
+    pub duplicate_sends_overridden: Counter,
+    /// Pending transactions given up on after the network's expiry deadline, by network
+    pub transactions_expired: IntCounterVec,
 
     // Security metrics
     pub login_attempts: Counter,
     pub failed_logins: Counter,
     pub quantum_encryptions: Counter,
     pub multisig_operations: Counter,
+    pub key_rotation_overdue: Counter,
 
     // Performance metrics
     pub active_connections: Gauge,
-    pub response_time: Histogram,
-    pub database_operations: Histogram,
+    /// Response time by route template (`unmatched` when no route matched)
+    pub response_time: HistogramVec,
+    /// Deprecated: unlabeled `response_time_seconds`, the sum over all routes.
+    /// Kept for one release while dashboards move to the labeled series.
+    pub response_time_aggregate: Histogram,
+    /// HTTP responses by status class (`2xx`, ...) and error class (`none` below 400)
+    pub http_requests: IntCounterVec,
+    /// Guarded storage queries by static query name
+    pub database_operations: HistogramVec,
+    /// Guarded queries that ran out of budget, by query name and `kind`
+    /// (`statement` interrupted, `acquire` waiting for a connection)
+    pub database_query_timeouts: IntCounterVec,
+    /// Connections per pool, by `state` (`total` / `idle`)
+    pub database_pool_connections: GaugeVec,
+    /// Longest connection wait per pool over the last sampling period
+    pub database_pool_acquire_wait: GaugeVec,
+    /// Expensive operations (signing, unlock, export) currently admitted
+    pub admission_in_flight: Gauge,
+    /// Requests waiting for an admission slot, by lane (`interactive` / `batch`)
+    pub admission_queue_depth: GaugeVec,
+    pub admission_wait_seconds: Histogram,
+    /// Requests turned away by admission control, by reason
+    pub admission_rejections: IntCounterVec,
+    /// Storage operations by pool (`writer` / `reader`)
+    pub database_pool_operations: IntCounterVec,
+    pub database_file_bytes: Gauge,
+    pub database_wal_bytes: Gauge,
+    pub database_page_count: Gauge,
+    pub database_freelist_count: Gauge,
+    /// WAL checkpoints by result (`completed`, `busy`, `deferred`)
+    pub wal_checkpoints: IntCounterVec,
 
     // Network metrics
-    pub blockchain_calls: Counter,
-    pub blockchain_errors: Counter,
-    pub network_latency: Histogram,
+    /// RPC calls sent to a node, by network
+    pub blockchain_calls: IntCounterVec,
+    /// RPC calls the node failed or answered with an error, by network
+    pub blockchain_errors: IntCounterVec,
+    /// RPC round trip, by network
+    pub network_latency: HistogramVec,
+    /// Deprecated unlabeled sums of the three above under their old names
+    /// (`blockchain_calls_total`, `blockchain_errors_total`,
+    /// `network_latency_seconds`); removed in the next release.
+    pub blockchain_calls_aggregate: Counter,
+    pub blockchain_errors_aggregate: Counter,
+    pub network_latency_aggregate: Histogram,
+    pub balance_snapshot_failures: Counter,
+    pub backup_failures: Counter,
+    /// Share of each outbound RPC token bucket in use, by endpoint host and window
+    pub rpc_bucket_utilization: GaugeVec,
+
+    // Process health
+    /// Panics caught by the process panic hook, by task name
+    pub process_panics: IntCounterVec,
+}
+
+impl std::fmt::Debug for WalletMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WalletMetrics").finish_non_exhaustive()
+    }
 }
 
 impl WalletMetrics {
@@ -64,6 +125,19 @@ impl WalletMetrics {
             "Transaction fees in native tokens",
         ))?;
 
+        let duplicate_sends_rejected = Counter::new(
+            "duplicate_sends_rejected_total",
+            "Sends refused as suspected duplicates of a pending transaction",
+        )?;
+        let duplicate_sends_overridden = Counter::new(
+            "duplicate_sends_overridden_total",
+            "Suspected duplicate sends let through with allow_duplicate",
+        )?;
+        let transactions_expired = IntCounterVec::new(
+            Opts::new("transactions_expired_total", "Pending transactions expired after dropping from the mempool"),
+            &["network"],
+        )?;
+
         // Security metrics
         let login_attempts =
             Counter::new("login_attempts_total", "Total number of login attempts")?;
@@ -74,27 +148,114 @@ impl WalletMetrics {
         )?;
         let multisig_operations =
             Counter::new("multisig_operations_total", "Total number of multisig operations")?;
+        let key_rotation_overdue = Counter::new(
+            "key_rotation_overdue_total",
+            "Signatures made with a key past its rotation policy",
+        )?;
 
         // Performance metrics
         let active_connections = Gauge::new("active_connections", "Number of active connections")?;
-        let response_time = Histogram::with_opts(HistogramOpts::new(
+        let response_time = HistogramVec::new(
+            HistogramOpts::new("response_time_by_route_seconds", "Response time in seconds by route"),
+            &["route"],
+        )?;
+        let response_time_aggregate = Histogram::with_opts(HistogramOpts::new(
             "response_time_seconds",
-            "Response time in seconds",
-        ))?;
-        let database_operations = Histogram::with_opts(HistogramOpts::new(
-            "database_operations_seconds",
-            "Database operation time in seconds",
+            "Response time in seconds (deprecated: use response_time_by_route_seconds)",
         ))?;
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP responses by status class and error class"),
+            &["status_class", "error_class"],
+        )?;
+        let database_operations = HistogramVec::new(
+            HistogramOpts::new("database_operations_seconds", "Database operation time in seconds")
+                .buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0]),
+            &["query"],
+        )?;
+        let database_query_timeouts = IntCounterVec::new(
+            Opts::new("database_query_timeouts_total", "Storage queries that exceeded their timeout"),
+            &["query", "kind"],
+        )?;
+        let database_pool_connections = GaugeVec::new(
+            Opts::new("database_pool_connections", "Connections held by each database pool"),
+            &["pool", "state"],
+        )?;
+        let database_pool_acquire_wait = GaugeVec::new(
+            Opts::new(
+                "database_pool_acquire_wait_seconds",
+                "Longest wait for a pooled connection since the last sample",
+            ),
+            &["pool"],
+        )?;
+        let database_pool_operations = IntCounterVec::new(
+            Opts::new("database_pool_operations_total", "Storage operations by connection pool"),
+            &["pool"],
+        )?;
+        let admission_in_flight =
+            Gauge::new("admission_in_flight", "Expensive operations currently holding an admission slot")?;
+        let admission_queue_depth = GaugeVec::new(
+            Opts::new("admission_queue_depth", "Requests waiting for an admission slot"),
+            &["lane"],
+        )?;
+        let admission_wait_seconds = Histogram::with_opts(
+            HistogramOpts::new("admission_wait_seconds", "Time spent queued before admission")
+                .buckets(vec![0.001, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]),
+        )?;
+        let admission_rejections = IntCounterVec::new(
+            Opts::new("admission_rejections_total", "Requests rejected by admission control by reason"),
+            &["reason"],
+        )?;
+        let database_file_bytes = Gauge::new("database_file_bytes", "Size of the main database file")?;
+        let database_wal_bytes = Gauge::new("database_wal_bytes", "Size of the database write-ahead log")?;
+        let database_page_count = Gauge::new("database_page_count", "Pages in the main database")?;
+        let database_freelist_count =
+            Gauge::new("database_freelist_count", "Unused pages in the main database")?;
+        let wal_checkpoints = IntCounterVec::new(
+            Opts::new("wal_checkpoints_total", "Explicit WAL checkpoints by result"),
+            &["result"],
+        )?;
 
         // Network metrics
-        let blockchain_calls =
-            Counter::new("blockchain_calls_total", "Total number of blockchain API calls")?;
-        let blockchain_errors =
-            Counter::new("blockchain_errors_total", "Total number of blockchain API errors")?;
-        let network_latency = Histogram::with_opts(HistogramOpts::new(
+        let blockchain_calls = IntCounterVec::new(
+            Opts::new("blockchain_calls_by_network_total", "Blockchain RPC calls by network"),
+            &["network"],
+        )?;
+        let blockchain_errors = IntCounterVec::new(
+            Opts::new("blockchain_errors_by_network_total", "Failed blockchain RPC calls by network"),
+            &["network"],
+        )?;
+        let network_latency = HistogramVec::new(
+            HistogramOpts::new("network_latency_by_network_seconds", "Blockchain RPC latency in seconds by network"),
+            &["network"],
+        )?;
+        let blockchain_calls_aggregate = Counter::new(
+            "blockchain_calls_total",
+            "Total number of blockchain API calls (deprecated: use blockchain_calls_by_network_total)",
+        )?;
+        let blockchain_errors_aggregate = Counter::new(
+            "blockchain_errors_total",
+            "Total number of blockchain API errors (deprecated: use blockchain_errors_by_network_total)",
+        )?;
+        let network_latency_aggregate = Histogram::with_opts(HistogramOpts::new(
             "network_latency_seconds",
-            "Network latency in seconds",
+            "Network latency in seconds (deprecated: use network_latency_by_network_seconds)",
         ))?;
+        let balance_snapshot_failures = Counter::new(
+            "balance_snapshot_failures_total",
+            "Balance snapshot fetches or writes that failed",
+        )?;
+        let backup_failures =
+            Counter::new("backup_failures_total", "Database backups that failed to write or verify")?;
+        let rpc_bucket_utilization = GaugeVec::new(
+            Opts::new("rpc_bucket_utilization", "Outbound RPC token bucket utilization (0-1)"),
+            &["endpoint", "window"],
+        )?;
+
+        // Process health
+        let process_panics = IntCounterVec::new(
+            Opts::new("process_panics_total", "Panics caught by the process panic hook"),
+            &["task"],
+        )?;
 
         // Register all metrics
         registry.register(Box::new(wallets_created.clone()))?;
@@ -104,16 +265,42 @@ impl WalletMetrics {
         registry.register(Box::new(transactions_failed.clone()))?;
         registry.register(Box::new(transaction_value.clone()))?;
         registry.register(Box::new(transaction_fees.clone()))?;
+        registry.register(Box::new(duplicate_sends_rejected.clone()))?;
+        registry.register(Box::new(duplicate_sends_overridden.clone()))?;
+        registry.register(Box::new(transactions_expired.clone()))?;
         registry.register(Box::new(login_attempts.clone()))?;
         registry.register(Box::new(failed_logins.clone()))?;
         registry.register(Box::new(quantum_encryptions.clone()))?;
         registry.register(Box::new(multisig_operations.clone()))?;
+        registry.register(Box::new(key_rotation_overdue.clone()))?;
         registry.register(Box::new(active_connections.clone()))?;
         registry.register(Box::new(response_time.clone()))?;
+        registry.register(Box::new(response_time_aggregate.clone()))?;
+        registry.register(Box::new(http_requests.clone()))?;
         registry.register(Box::new(database_operations.clone()))?;
+        registry.register(Box::new(database_query_timeouts.clone()))?;
+        registry.register(Box::new(database_pool_connections.clone()))?;
+        registry.register(Box::new(database_pool_acquire_wait.clone()))?;
+        registry.register(Box::new(database_pool_operations.clone()))?;
+        registry.register(Box::new(admission_in_flight.clone()))?;
+        registry.register(Box::new(admission_queue_depth.clone()))?;
+        registry.register(Box::new(admission_wait_seconds.clone()))?;
+        registry.register(Box::new(admission_rejections.clone()))?;
+        registry.register(Box::new(database_file_bytes.clone()))?;
+        registry.register(Box::new(database_wal_bytes.clone()))?;
+        registry.register(Box::new(database_page_count.clone()))?;
+        registry.register(Box::new(database_freelist_count.clone()))?;
+        registry.register(Box::new(wal_checkpoints.clone()))?;
         registry.register(Box::new(blockchain_calls.clone()))?;
         registry.register(Box::new(blockchain_errors.clone()))?;
         registry.register(Box::new(network_latency.clone()))?;
+        registry.register(Box::new(blockchain_calls_aggregate.clone()))?;
+        registry.register(Box::new(blockchain_errors_aggregate.clone()))?;
+        registry.register(Box::new(network_latency_aggregate.clone()))?;
+        registry.register(Box::new(balance_snapshot_failures.clone()))?;
+        registry.register(Box::new(backup_failures.clone()))?;
+        registry.register(Box::new(rpc_bucket_utilization.clone()))?;
+        registry.register(Box::new(process_panics.clone()))?;
 
         info!("鉁?Wallet metrics initialized");
 
@@ -126,16 +313,42 @@ impl WalletMetrics {
             transactions_failed,
             transaction_value,
             transaction_fees,
+            duplicate_sends_rejected,
+            duplicate_sends_overridden,
+            transactions_expired,
             login_attempts,
             failed_logins,
             quantum_encryptions,
             multisig_operations,
+            key_rotation_overdue,
             active_connections,
             response_time,
+            response_time_aggregate,
+            http_requests,
             database_operations,
+            database_query_timeouts,
+            database_pool_connections,
+            database_pool_acquire_wait,
+            database_pool_operations,
+            admission_in_flight,
+            admission_queue_depth,
+            admission_wait_seconds,
+            admission_rejections,
+            database_file_bytes,
+            database_wal_bytes,
+            database_page_count,
+            database_freelist_count,
+            wal_checkpoints,
             blockchain_calls,
             blockchain_errors,
             network_latency,
+            blockchain_calls_aggregate,
+            blockchain_errors_aggregate,
+            network_latency_aggregate,
+            balance_snapshot_failures,
+            backup_failures,
+            rpc_bucket_utilization,
+            process_panics,
         })
     }
 
@@ -161,10 +374,11 @@ impl WalletMetrics {
         warn!("馃搳 Recorded wallet deletion");
     }
 
-    pub fn record_transaction_sent(&self, value: f64, fee: f64) {
+    /// 直方图按主单位观测，换算成 f64 是近似值；日志里的金额是精确的
+    pub fn record_transaction_sent(&self, value: &Amount, fee: &Amount) {
         self.transactions_sent.inc();
-        self.transaction_value.observe(value);
-        self.transaction_fees.observe(fee);
+        self.transaction_value.observe(value.to_f64_lossy());
+        self.transaction_fees.observe(fee.to_f64_lossy());
         info!("馃搳 Recorded successful transaction: value={}, fee={}", value, fee);
     }
 
@@ -173,6 +387,18 @@ impl WalletMetrics {
         error!("馃搳 Recorded failed transaction");
     }
 
+    pub fn record_duplicate_send(&self, overridden: bool) {
+        if overridden {
+            self.duplicate_sends_overridden.inc();
+        } else {
+            self.duplicate_sends_rejected.inc();
+        }
+    }
+
+    pub fn record_transaction_expired(&self, network: &str) {
+        self.transactions_expired.with_label_values(&[network]).inc();
+    }
+
     pub fn record_login_attempt(&self, success: bool) {
         self.login_attempts.inc();
         if !success {
@@ -189,33 +415,106 @@ impl WalletMetrics {
         self.multisig_operations.inc();
     }
 
+    pub fn record_key_rotation_overdue(&self) {
+        self.key_rotation_overdue.inc();
+    }
+
     pub fn set_active_connections(&self, count: f64) {
         self.active_connections.set(count);
     }
 
-    pub fn record_response_time(&self, duration: f64) {
-        self.response_time.observe(duration);
+    /// `route` is the matched route template, never the concrete path
+    pub fn record_response_time(&self, route: &str, duration: f64) {
+        self.response_time.with_label_values(&[route]).observe(duration);
+        self.response_time_aggregate.observe(duration);
+    }
+
+    /// `error_class` is `none` for responses below 400
+    pub fn record_http_request(&self, status_class: &str, error_class: &str, route: &str, duration: f64) {
+        self.http_requests.with_label_values(&[status_class, error_class]).inc();
+        self.record_response_time(route, duration);
     }
 
-    pub fn record_database_operation(&self, duration: f64) {
-        self.database_operations.observe(duration);
+    pub fn record_database_operation(&self, query: &str, duration: f64) {
+        self.database_operations.with_label_values(&[query]).observe(duration);
     }
 
-    pub fn record_blockchain_call(&self, success: bool, latency: f64) {
-        self.blockchain_calls.inc();
-        self.network_latency.observe(latency);
+    /// `kind` is `statement` or `acquire`
+    pub fn record_database_timeout(&self, query: &str, kind: &str) {
+        self.database_query_timeouts.with_label_values(&[query, kind]).inc();
+    }
+
+    pub fn set_database_pool_stats(&self, pool: &str, size: u32, idle: usize, acquire_wait: f64) {
+        self.database_pool_connections.with_label_values(&[pool, "total"]).set(size as f64);
+        self.database_pool_connections.with_label_values(&[pool, "idle"]).set(idle as f64);
+        self.database_pool_acquire_wait.with_label_values(&[pool]).set(acquire_wait);
+    }
+
+    pub fn set_database_file_stats(&self, db_bytes: u64, wal_bytes: u64, page_count: i64, freelist_count: i64) {
+        self.database_file_bytes.set(db_bytes as f64);
+        self.database_wal_bytes.set(wal_bytes as f64);
+        self.database_page_count.set(page_count as f64);
+        self.database_freelist_count.set(freelist_count as f64);
+    }
+
+    pub fn record_wal_checkpoint(&self, result: &str) {
+        self.wal_checkpoints.with_label_values(&[result]).inc();
+    }
+
+    pub fn record_database_pool_operation(&self, pool: &str) {
+        self.database_pool_operations.with_label_values(&[pool]).inc();
+    }
+
+    pub fn record_blockchain_call(&self, network: &str, success: bool, latency: f64) {
+        self.blockchain_calls.with_label_values(&[network]).inc();
+        self.network_latency.with_label_values(&[network]).observe(latency);
+        self.blockchain_calls_aggregate.inc();
+        self.network_latency_aggregate.observe(latency);
 
         if !success {
-            self.blockchain_errors.inc();
-            warn!("馃搳 Recorded blockchain API error");
+            self.blockchain_errors.with_label_values(&[network]).inc();
+            self.blockchain_errors_aggregate.inc();
+            warn!("馃搳 Recorded blockchain API error on {}", network);
         }
     }
+
+    pub fn record_balance_snapshot_failure(&self) {
+        self.balance_snapshot_failures.inc();
+    }
+
+    pub fn record_backup_failure(&self) {
+        self.backup_failures.inc();
+    }
+
+    pub fn set_rpc_bucket_utilization(&self, endpoint: &str, window: &str, utilization: f64) {
+        self.rpc_bucket_utilization.with_label_values(&[endpoint, window]).set(utilization);
+    }
+
+    pub fn set_admission_state(&self, in_flight: usize, interactive_queued: usize, batch_queued: usize) {
+        self.admission_in_flight.set(in_flight as f64);
+        self.admission_queue_depth.with_label_values(&["interactive"]).set(interactive_queued as f64);
+        self.admission_queue_depth.with_label_values(&["batch"]).set(batch_queued as f64);
+    }
+
+    pub fn record_admission_wait(&self, seconds: f64) {
+        self.admission_wait_seconds.observe(seconds);
+    }
+
+    pub fn record_admission_rejection(&self, reason: &str) {
+        self.admission_rejections.with_label_values(&[reason]).inc();
+    }
+
+    pub fn record_panic(&self, task: &str) {
+        self.process_panics.with_label_values(&[task]).inc();
+    }
 }
 
 pub struct SecurityMonitor {
     #[allow(dead_code)]
     metrics: Arc<WalletMetrics>,
     suspicious_activity: Arc<Mutex<Vec<SecurityEvent>>>,
+    /// High and Critical events are also written to the events journal
+    journal: Option<Arc<crate::storage::WalletStorage>>,
 }
 
 #[derive(Debug, Clone)]
@@ -236,6 +535,8 @@ pub enum SecurityEventType {
     UnusualLocation,
     QuantumAttackAttempt,
     MalformedRequest,
+    KeyRotationOverdue,
+    DeadmanSwitchTriggered,
 }
 
 #[derive(Debug, Clone)]
@@ -250,7 +551,12 @@ impl SecurityMonitor {
     pub fn new(metrics: Arc<WalletMetrics>) -> Self {
         info!("馃洝锔?Initializing security monitor");
 
-        Self { metrics, suspicious_activity: Arc::new(Mutex::new(Vec::new())) }
+        Self { metrics, suspicious_activity: Arc::new(Mutex::new(Vec::new())), journal: None }
+    }
+
+    pub fn with_journal(mut self, storage: Arc<crate::storage::WalletStorage>) -> Self {
+        self.journal = Some(storage);
+        self
     }
 
     pub async fn report_security_event(&self, event: SecurityEvent) {
@@ -269,6 +575,8 @@ impl SecurityMonitor {
             SecurityEventType::UnusualLocation => "UnusualLocation",
             SecurityEventType::QuantumAttackAttempt => "QuantumAttackAttempt",
             SecurityEventType::MalformedRequest => "MalformedRequest",
+            SecurityEventType::KeyRotationOverdue => "KeyRotationOverdue",
+            SecurityEventType::DeadmanSwitchTriggered => "DeadmanSwitchTriggered",
         };
 
         let redacted_description = redact_body(&event.description);
@@ -279,6 +587,30 @@ impl SecurityMonitor {
             redacted_description
         );
 
+        // The description is free text and may quote request data; only
+        // structured fields are journaled.
+        if let (Some(journal), SecuritySeverity::High | SecuritySeverity::Critical) =
+            (&self.journal, &event.severity)
+        {
+            let entity_id = event.wallet_id.clone().unwrap_or_default();
+            let result = journal
+                .record_event(&crate::storage::NewJournalEvent {
+                    event_type: crate::storage::journal_events::SECURITY_EVENT,
+                    entity_type: "security",
+                    entity_id: &entity_id,
+                    payload: serde_json::json!({
+                        "kind": event_type_str,
+                        "severity": severity_str,
+                        "wallet_id": event.wallet_id,
+                        "occurred_at": event.timestamp,
+                    }),
+                })
+                .await;
+            if let Err(e) = result {
+                warn!("failed to journal security event: {}", e);
+            }
+        }
+
         // Store the event
         let mut events = self.suspicious_activity.lock().await;
         events.push(event.clone());
@@ -350,6 +682,8 @@ impl SecurityMonitor {
             SecurityEventType::UnusualLocation => "UnusualLocation",
             SecurityEventType::QuantumAttackAttempt => "QuantumAttackAttempt",
             SecurityEventType::MalformedRequest => "MalformedRequest",
+            SecurityEventType::KeyRotationOverdue => "KeyRotationOverdue",
+            SecurityEventType::DeadmanSwitchTriggered => "DeadmanSwitchTriggered",
         };
 
         let redacted_desc = redact_body(&event.description);
@@ -406,7 +740,10 @@ mod tests {
 
         // Test recording some metrics
         metrics.record_wallet_created();
-        metrics.record_transaction_sent(1.5, 0.001);
+        metrics.record_transaction_sent(
+            &Amount::parse_native("eth", "1.5").unwrap(),
+            &Amount::parse_native("eth", "0.001").unwrap(),
+        );
         metrics.record_login_attempt(true);
         metrics.record_login_attempt(false);
 
@@ -417,6 +754,37 @@ mod tests {
         assert!(exported.contains("login_attempts_total"));
     }
 
+    #[test]
+    fn test_labeled_series_and_aggregates() {
+        let metrics = WalletMetrics::new().unwrap();
+        metrics.record_blockchain_call("eth", true, 0.2);
+        metrics.record_blockchain_call("eth", false, 0.4);
+        metrics.record_blockchain_call("bsc", false, 1.5);
+        metrics.record_http_request("2xx", "none", "/api/wallets/:name/balance", 0.05);
+        metrics.record_http_request("4xx", "not_found", "unmatched", 0.01);
+        metrics.record_response_time("/api/health", 0.02);
+
+        let exported = metrics.export_metrics().unwrap();
+        for series in [
+            r#"blockchain_calls_by_network_total{network="eth"} 2"#,
+            r#"blockchain_calls_by_network_total{network="bsc"} 1"#,
+            r#"blockchain_errors_by_network_total{network="eth"} 1"#,
+            r#"blockchain_errors_by_network_total{network="bsc"} 1"#,
+            r#"network_latency_by_network_seconds_count{network="eth"} 2"#,
+            r#"network_latency_by_network_seconds_sum{network="bsc"} 1.5"#,
+            r#"response_time_by_route_seconds_count{route="/api/wallets/:name/balance"} 1"#,
+            r#"response_time_by_route_seconds_count{route="unmatched"} 1"#,
+            r#"response_time_by_route_seconds_count{route="/api/health"} 1"#,
+            // the unlabeled names still carry the sums
+            "blockchain_calls_total 3",
+            "blockchain_errors_total 2",
+            "network_latency_seconds_count 3",
+            "response_time_seconds_count 3",
+        ] {
+            assert!(exported.lines().any(|line| line == series), "missing {}:\n{}", series, exported);
+        }
+    }
+
     #[tokio::test]
     async fn test_security_monitor() {
         let metrics = Arc::new(WalletMetrics::new().unwrap());
@@ -0,0 +1,547 @@
+//! Typed bundle definitions and the builder that validates them.
+//!
+//! A bundle is an ordered list of steps run by one wallet. A step may declare
+//! dependencies on earlier steps; dependencies only ever point backwards, so
+//! the order given is also a valid execution order and cycles cannot be
+//! expressed. An amount placeholder must name one of the step's declared
+//! dependencies, and that dependency must be a step that produces an amount.
+
+use ethers::types::{Address, Bytes};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::placeholder::{AmountSpec, PlaceholderError};
+use super::StepOutput;
+use crate::pricing::native_symbol;
+
+/// Steps per bundle
+pub const MAX_BUNDLE_STEPS: usize = 16;
+/// Step id: 1-32 of `[A-Za-z0-9_-]`
+const MAX_STEP_ID_LEN: usize = 32;
+/// Upper bound accepted for swap slippage (percent)
+const MAX_SLIPPAGE_PERCENT: f64 = 50.0;
+
+/// What happens to the remaining steps once one fails
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// Skip every remaining step
+    #[default]
+    AbortRemaining,
+    /// Keep going; only steps depending on the failed one are skipped
+    Continue,
+}
+
+impl FailurePolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FailurePolicy::AbortRemaining => "abort_remaining",
+            FailurePolicy::Continue => "continue",
+        }
+    }
+}
+
+impl std::str::FromStr for FailurePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "abort_remaining" => Ok(FailurePolicy::AbortRemaining),
+            "continue" => Ok(FailurePolicy::Continue),
+            other => Err(format!("unknown failure policy {:?}", other)),
+        }
+    }
+}
+
+fn default_token_decimals() -> u32 {
+    18
+}
+
+/// One operation; amounts are decimal whole units
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StepAction {
+    /// ERC-20 `approve(spender, amount)`
+    Approve {
+        network: String,
+        token: Address,
+        spender: Address,
+        amount: AmountSpec,
+        /// Token decimals used to encode `amount`
+        #[serde(default = "default_token_decimals")]
+        decimals: u32,
+        /// Approve `U256::MAX` instead of `amount`, unless the token is
+        /// flagged `max_approval_disallowed`
+        #[serde(default)]
+        unlimited: bool,
+    },
+    /// DEX swap; produces the amount of `to_token` received
+    Swap { network: String, from_token: String, to_token: String, amount: AmountSpec, slippage: f64 },
+    /// Native transfer
+    Transfer {
+        network: String,
+        to: Address,
+        amount: AmountSpec,
+        #[serde(default)]
+        acknowledge_contract_recipient: bool,
+    },
+    /// Cross-chain transfer; produces the amount sent to `to_chain`
+    Bridge { from_chain: String, to_chain: String, token: String, amount: AmountSpec },
+    /// Arbitrary call with optional native value
+    ContractCall {
+        network: String,
+        to: Address,
+        #[serde(default)]
+        data: Bytes,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<AmountSpec>,
+    },
+}
+
+impl StepAction {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StepAction::Approve { .. } => "approve",
+            StepAction::Swap { .. } => "swap",
+            StepAction::Transfer { .. } => "transfer",
+            StepAction::Bridge { .. } => "bridge",
+            StepAction::ContractCall { .. } => "contract_call",
+        }
+    }
+
+    /// Networks the step touches (both legs for a bridge)
+    pub fn networks(&self) -> Vec<&str> {
+        match self {
+            StepAction::Approve { network, .. }
+            | StepAction::Swap { network, .. }
+            | StepAction::Transfer { network, .. }
+            | StepAction::ContractCall { network, .. } => vec![network.as_str()],
+            StepAction::Bridge { from_chain, to_chain, .. } => vec![from_chain.as_str(), to_chain.as_str()],
+        }
+    }
+
+    /// Mutable access to the same fields as [`StepAction::networks`], for
+    /// normalizing network aliases.
+    pub fn networks_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            StepAction::Approve { network, .. }
+            | StepAction::Swap { network, .. }
+            | StepAction::Transfer { network, .. }
+            | StepAction::ContractCall { network, .. } => vec![network],
+            StepAction::Bridge { from_chain, to_chain, .. } => vec![from_chain, to_chain],
+        }
+    }
+
+    /// Whether the step's output carries an amount later steps can spend
+    pub fn produces_amount(&self) -> bool {
+        matches!(self, StepAction::Swap { .. } | StepAction::Bridge { .. })
+    }
+
+    /// Whether a step found in flight after a restart may simply run again.
+    /// A transfer resent while the first is pending is refused by duplicate
+    /// detection, and a repeated approve sets the same allowance; for the
+    /// others a second attempt could move funds twice.
+    pub fn retry_safe(&self) -> bool {
+        matches!(self, StepAction::Transfer { .. } | StepAction::Approve { .. })
+    }
+
+    fn amounts(&self) -> Vec<&AmountSpec> {
+        match self {
+            StepAction::Approve { amount, .. }
+            | StepAction::Swap { amount, .. }
+            | StepAction::Transfer { amount, .. }
+            | StepAction::Bridge { amount, .. } => vec![amount],
+            StepAction::ContractCall { value, .. } => value.iter().collect(),
+        }
+    }
+
+    fn amounts_mut(&mut self) -> Vec<&mut AmountSpec> {
+        match self {
+            StepAction::Approve { amount, .. }
+            | StepAction::Swap { amount, .. }
+            | StepAction::Transfer { amount, .. }
+            | StepAction::Bridge { amount, .. } => vec![amount],
+            StepAction::ContractCall { value, .. } => value.iter_mut().collect(),
+        }
+    }
+
+    /// The step with every placeholder replaced by the referenced output
+    pub fn resolve(&self, outputs: &HashMap<String, StepOutput>) -> Result<StepAction, PlaceholderError> {
+        let mut resolved = self.clone();
+        for amount in resolved.amounts_mut() {
+            *amount = amount.substitute(outputs)?;
+        }
+        Ok(resolved)
+    }
+
+    /// Native funds the step takes out of the wallet, keyed by network.
+    /// Placeholder amounts spend what an earlier step produced and are
+    /// already accounted for by that step's own input; approvals move nothing.
+    fn native_outflow(&self) -> Option<(&str, Decimal)> {
+        let is_native = |network: &str, token: &str| {
+            native_symbol(network).is_some_and(|symbol| symbol.eq_ignore_ascii_case(token))
+        };
+        match self {
+            StepAction::Approve { .. } => None,
+            StepAction::Transfer { network, amount, .. } => Some((network.as_str(), amount.value().ok()?)),
+            StepAction::ContractCall { network, value, .. } => {
+                Some((network.as_str(), value.as_ref()?.value().ok()?))
+            }
+            StepAction::Swap { network, from_token, amount, .. } if is_native(network, from_token) => {
+                Some((network.as_str(), amount.value().ok()?))
+            }
+            StepAction::Bridge { from_chain, token, amount, .. } if is_native(from_chain, token) => {
+                Some((from_chain.as_str(), amount.value().ok()?))
+            }
+            StepAction::Swap { .. } | StepAction::Bridge { .. } => None,
+        }
+    }
+
+    /// Addresses the wallet hands value or an allowance to, for screening:
+    /// `(network, address, is_contract)`
+    fn counterparty(&self) -> Option<(&str, Address, bool)> {
+        match self {
+            StepAction::Transfer { network, to, .. } => Some((network.as_str(), *to, false)),
+            StepAction::Approve { network, spender, .. } => Some((network.as_str(), *spender, true)),
+            StepAction::ContractCall { network, to, .. } => Some((network.as_str(), *to, true)),
+            StepAction::Swap { .. } | StepAction::Bridge { .. } => None,
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        match self {
+            StepAction::Swap { from_token, to_token, slippage, .. } => {
+                if from_token.trim().is_empty() || to_token.trim().is_empty() {
+                    return Err("from_token and to_token are required".to_string());
+                }
+                if from_token.eq_ignore_ascii_case(to_token) {
+                    return Err("from_token and to_token must differ".to_string());
+                }
+                if !(0.0..=MAX_SLIPPAGE_PERCENT).contains(slippage) {
+                    return Err(format!("slippage must be between 0 and {}%", MAX_SLIPPAGE_PERCENT));
+                }
+            }
+            StepAction::Bridge { from_chain, to_chain, token, .. } => {
+                if from_chain == to_chain {
+                    return Err("from_chain and to_chain must differ".to_string());
+                }
+                if token.trim().is_empty() {
+                    return Err("token is required".to_string());
+                }
+            }
+            StepAction::Approve { decimals, .. } if *decimals > 36 => {
+                return Err("decimals must be at most 36".to_string());
+            }
+            StepAction::ContractCall { data, value, .. } if data.is_empty() && value.is_none() => {
+                return Err("a contract call needs data or a value".to_string());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// A step as submitted: an id, the ids it depends on, and the action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleStep {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    #[serde(flatten)]
+    pub action: StepAction,
+}
+
+impl BundleStep {
+    pub fn new(id: impl Into<String>, action: StepAction) -> Self {
+        Self { id: id.into(), depends_on: Vec::new(), action }
+    }
+
+    /// Declares a dependency on an earlier step.
+    pub fn after(mut self, step: impl Into<String>) -> Self {
+        self.depends_on.push(step.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BundleError {
+    #[error("A bundle needs at least one step")]
+    Empty,
+    #[error("A bundle may have at most {0} steps")]
+    TooManySteps(usize),
+    #[error("Step id {0:?} must be 1-32 letters, digits, '_' or '-'")]
+    StepId(String),
+    #[error("Step id {0} is used twice")]
+    DuplicateStep(String),
+    #[error("Step {step} depends on {dependency}, which is not an earlier step")]
+    UnknownDependency { step: String, dependency: String },
+    #[error("Step {step} uses the output of {referenced} without declaring it in depends_on")]
+    UndeclaredPlaceholder { step: String, referenced: String },
+    #[error("Step {step} uses the amount of {referenced}, but a {kind} step produces no amount")]
+    NoAmountOutput { step: String, referenced: String, kind: &'static str },
+    #[error("Step {step}: {reason}")]
+    InvalidStep { step: String, reason: String },
+}
+
+/// A validated bundle; build one with [`BundleBuilder`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Bundle {
+    pub wallet_name: String,
+    pub failure_policy: FailurePolicy,
+    pub steps: Vec<BundleStep>,
+}
+
+impl Bundle {
+    pub fn builder(wallet_name: impl Into<String>) -> BundleBuilder {
+        BundleBuilder::new(wallet_name)
+    }
+
+    /// Every network any step touches
+    pub fn networks(&self) -> BTreeSet<&str> {
+        self.steps.iter().flat_map(|s| s.action.networks()).collect()
+    }
+
+    /// Total native outflow per network; limits that apply to one send apply
+    /// to these totals, so splitting a transfer into steps does not get
+    /// around them.
+    pub fn native_outflow(&self) -> BTreeMap<String, Decimal> {
+        let mut totals: BTreeMap<String, Decimal> = BTreeMap::new();
+        for (network, amount) in self.steps.iter().filter_map(|s| s.action.native_outflow()) {
+            *totals.entry(network.to_string()).or_default() += amount;
+        }
+        totals
+    }
+
+    /// `(step id, network, address, is_contract)` of every counterparty
+    pub fn counterparties<'a>(&'a self) -> Vec<(&'a str, &'a str, Address, bool)> {
+        let with_id = |step: &'a BundleStep| {
+            let (network, address, is_contract) = step.action.counterparty()?;
+            Some((step.id.as_str(), network, address, is_contract))
+        };
+        self.steps.iter().filter_map(with_id).collect()
+    }
+}
+
+/// Collects steps and validates the bundle as a whole in [`BundleBuilder::build`]
+#[derive(Debug, Clone)]
+pub struct BundleBuilder {
+    wallet_name: String,
+    failure_policy: FailurePolicy,
+    steps: Vec<BundleStep>,
+}
+
+impl BundleBuilder {
+    pub fn new(wallet_name: impl Into<String>) -> Self {
+        Self { wallet_name: wallet_name.into(), failure_policy: FailurePolicy::default(), steps: Vec::new() }
+    }
+
+    pub fn failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
+    pub fn step(mut self, step: BundleStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn steps(mut self, steps: impl IntoIterator<Item = BundleStep>) -> Self {
+        self.steps.extend(steps);
+        self
+    }
+
+    pub fn build(self) -> Result<Bundle, BundleError> {
+        if self.steps.is_empty() {
+            return Err(BundleError::Empty);
+        }
+        if self.steps.len() > MAX_BUNDLE_STEPS {
+            return Err(BundleError::TooManySteps(MAX_BUNDLE_STEPS));
+        }
+        // step id -> (kind, produces an amount), for the steps before the one being checked
+        let mut earlier: HashMap<&str, (&'static str, bool)> = HashMap::new();
+        for step in &self.steps {
+            let id = step.id.as_str();
+            let valid_id = !id.is_empty()
+                && id.len() <= MAX_STEP_ID_LEN
+                && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+            if !valid_id {
+                return Err(BundleError::StepId(step.id.clone()));
+            }
+            if earlier.contains_key(id) {
+                return Err(BundleError::DuplicateStep(step.id.clone()));
+            }
+            if let Some(dependency) = step.depends_on.iter().find(|d| !earlier.contains_key(d.as_str())) {
+                return Err(BundleError::UnknownDependency { step: step.id.clone(), dependency: dependency.clone() });
+            }
+            for referenced in step.action.amounts().into_iter().filter_map(AmountSpec::source) {
+                if !step.depends_on.iter().any(|d| d == referenced) {
+                    return Err(BundleError::UndeclaredPlaceholder {
+                        step: step.id.clone(),
+                        referenced: referenced.to_string(),
+                    });
+                }
+                let (kind, produces_amount) = earlier[referenced];
+                if !produces_amount {
+                    return Err(BundleError::NoAmountOutput {
+                        step: step.id.clone(),
+                        referenced: referenced.to_string(),
+                        kind,
+                    });
+                }
+            }
+            step.action.check().map_err(|reason| BundleError::InvalidStep { step: step.id.clone(), reason })?;
+            earlier.insert(id, (step.action.kind(), step.action.produces_amount()));
+        }
+        Ok(Bundle { wallet_name: self.wallet_name, failure_policy: self.failure_policy, steps: self.steps })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const ROUTER: &str = "0x1111111254eeb25477b68fb85ed929f73a960582";
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    fn amount(text: &str) -> AmountSpec {
+        AmountSpec::parse(text).unwrap()
+    }
+
+    fn approve() -> BundleStep {
+        BundleStep::new(
+            "approve",
+            StepAction::Approve {
+                network: "eth".to_string(),
+                token: USDC.parse().unwrap(),
+                spender: ROUTER.parse().unwrap(),
+                amount: amount("1000"),
+                decimals: 6,
+                unlimited: false,
+            },
+        )
+    }
+
+    fn swap(from: &str, to: &str, value: &str) -> BundleStep {
+        BundleStep::new(
+            "swap",
+            StepAction::Swap {
+                network: "eth".to_string(),
+                from_token: from.to_string(),
+                to_token: to.to_string(),
+                amount: amount(value),
+                slippage: 0.5,
+            },
+        )
+    }
+
+    fn bridge(value: &str) -> BundleStep {
+        BundleStep::new(
+            "bridge",
+            StepAction::Bridge {
+                from_chain: "eth".to_string(),
+                to_chain: "polygon".to_string(),
+                token: "ETH".to_string(),
+                amount: amount(value),
+            },
+        )
+    }
+
+    fn transfer(id: &str, value: &str) -> BundleStep {
+        BundleStep::new(
+            id,
+            StepAction::Transfer {
+                network: "eth".to_string(),
+                to: ROUTER.parse().unwrap(),
+                amount: amount(value),
+                acknowledge_contract_recipient: false,
+            },
+        )
+    }
+
+    #[test]
+    fn test_builds_approve_swap_bridge() {
+        let bundle = Bundle::builder("treasury")
+            .step(approve())
+            .step(swap("USDC", "ETH", "1000").after("approve"))
+            .step(bridge("{{steps.swap.amount}}").after("swap"))
+            .build()
+            .unwrap();
+        assert_eq!(bundle.failure_policy, FailurePolicy::AbortRemaining);
+        assert_eq!(bundle.networks().into_iter().collect::<Vec<_>>(), ["eth", "polygon"]);
+        // a USDC swap and a bridge of its output take no ETH out of the wallet
+        assert!(bundle.native_outflow().is_empty());
+        assert_eq!(bundle.counterparties().len(), 1);
+    }
+
+    #[test]
+    fn test_dependencies_must_point_backwards() {
+        let err = Bundle::builder("w").step(swap("USDC", "ETH", "1").after("approve")).step(approve()).build();
+        assert_eq!(
+            err.unwrap_err(),
+            BundleError::UnknownDependency { step: "swap".to_string(), dependency: "approve".to_string() }
+        );
+        let err = Bundle::builder("w").step(transfer("a", "1")).step(transfer("a", "2")).build();
+        assert_eq!(err.unwrap_err(), BundleError::DuplicateStep("a".to_string()));
+        let err = Bundle::builder("w").step(transfer("a.b", "1")).build();
+        assert_eq!(err.unwrap_err(), BundleError::StepId("a.b".to_string()));
+        assert_eq!(Bundle::builder("w").build().unwrap_err(), BundleError::Empty);
+    }
+
+    #[test]
+    fn test_placeholders_need_a_declared_amount_producing_source() {
+        let err = Bundle::builder("w")
+            .step(swap("USDC", "ETH", "1000"))
+            .step(bridge("{{steps.swap.amount}}"))
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, BundleError::UndeclaredPlaceholder { .. }));
+
+        let err = Bundle::builder("w")
+            .step(approve())
+            .step(bridge("{{steps.approve.amount}}").after("approve"))
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, BundleError::NoAmountOutput { kind: "approve", .. }));
+    }
+
+    #[test]
+    fn test_native_outflow_sums_literal_amounts_per_network() {
+        let bundle = Bundle::builder("w")
+            .step(transfer("a", "0.6"))
+            .step(swap("ETH", "USDC", "0.25"))
+            .step(transfer("b", "0.6"))
+            .build()
+            .unwrap();
+        let outflow = bundle.native_outflow();
+        assert_eq!(outflow["eth"], Decimal::from_str("1.45").unwrap());
+    }
+
+    #[test]
+    fn test_resolve_substitutes_only_placeholders() {
+        let step = bridge("{{steps.swap.amount}}");
+        let outputs = HashMap::from([(
+            "swap".to_string(),
+            StepOutput { amount: Some("0.5".to_string()), ..Default::default() },
+        )]);
+        let StepAction::Bridge { amount, .. } = step.action.resolve(&outputs).unwrap() else { unreachable!() };
+        assert_eq!(amount, AmountSpec::literal(Decimal::from_str("0.5").unwrap()));
+    }
+
+    #[test]
+    fn test_steps_deserialize_from_tagged_json() {
+        let step: BundleStep = serde_json::from_value(serde_json::json!({
+            "id": "bridge",
+            "kind": "bridge",
+            "depends_on": ["swap"],
+            "from_chain": "eth",
+            "to_chain": "polygon",
+            "token": "ETH",
+            "amount": "{{steps.swap.amount}}",
+        }))
+        .unwrap();
+        assert_eq!(step, bridge("{{steps.swap.amount}}").after("swap"));
+        assert_eq!(serde_json::to_value(&step).unwrap()["kind"], "bridge");
+    }
+}
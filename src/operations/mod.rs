@@ -0,0 +1,363 @@
+//! Multi-step operation bundles (approve -> swap -> bridge and the like)
+//! submitted as one request.
+//!
+//! [`Bundle`] checks the shape up front: step ids, backward-only
+//! dependencies, and placeholders that point at an amount-producing step.
+//! [`BundleService`] persists the bundle, screens its counterparties, and
+//! runs the steps in order through a [`StepExecutor`], recording each step's
+//! status and output as it goes. A bundle cut short by a restart is marked
+//! `interrupted` and resumes from the first unfinished step; completed steps
+//! are never executed twice.
+
+pub mod bundle;
+pub mod placeholder;
+
+use async_trait::async_trait;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::anomaly_detection::{AnomalyDetectionConfig, AnomalyDetector};
+use crate::feature_flags::{FeatureFlags, Flag};
+use crate::intents::DecisionContext;
+use crate::storage::{
+    BundleRecord, BundleStepRecord, NewBundle, NewBundleStep, StepProgress, WalletStorage, BUNDLE_COMPLETED,
+    BUNDLE_FAILED, BUNDLE_INTERRUPTED, BUNDLE_PARTIALLY_COMPLETED, BUNDLE_PENDING, BUNDLE_RUNNING, STEP_COMPLETED,
+    STEP_FAILED, STEP_RUNNING, STEP_SKIPPED,
+};
+
+pub use bundle::{Bundle, BundleBuilder, BundleError, BundleStep, FailurePolicy, StepAction, MAX_BUNDLE_STEPS};
+pub use placeholder::{AmountSpec, PlaceholderError};
+
+/// What a completed step produced
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepOutput {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    /// Tracking id of a step without a transaction hash of its own (a bridge)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// Decimal whole units of `asset`; this is what `{{steps.<id>.amount}}`
+    /// substitutes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+    /// Where `amount` now sits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+/// Why a step failed; `code` uses the API's error codes
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{code}: {message}")]
+pub struct StepFailure {
+    pub code: String,
+    pub message: String,
+}
+
+impl StepFailure {
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { code: code.into(), message: message.into() }
+    }
+}
+
+/// Executes one resolved step; abstracted so the service needs no RPC node.
+#[async_trait]
+pub trait StepExecutor: Send + Sync {
+    async fn execute(&self, step_id: &str, action: &StepAction) -> Result<StepOutput, StepFailure>;
+}
+
+/// Why a bundle was not accepted or not run. `code()` is part of the API
+/// contract.
+#[derive(Debug, thiserror::Error)]
+pub enum BundleRunError {
+    #[error("Bundle not found")]
+    NotFound,
+    #[error("Bundle is {0} and cannot be run")]
+    NotRunnable(String),
+    #[error("Step {step} blocked by anomaly detection: {reason}")]
+    Blocked { step: String, reason: String },
+    #[error("Bundle {0} was changed by another runner")]
+    Conflict(String),
+    #[error("Stored bundle is unreadable: {0}")]
+    Corrupt(String),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+impl BundleRunError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            BundleRunError::NotFound => "BUNDLE_NOT_FOUND",
+            BundleRunError::NotRunnable(_) => "BUNDLE_NOT_RUNNABLE",
+            BundleRunError::Blocked { .. } => "BUNDLE_BLOCKED",
+            BundleRunError::Conflict(_) => "BUNDLE_CONFLICT",
+            BundleRunError::Corrupt(_) | BundleRunError::Storage(_) => "DB_ERROR",
+        }
+    }
+}
+
+fn corrupt(e: impl std::fmt::Display) -> BundleRunError {
+    BundleRunError::Corrupt(e.to_string())
+}
+
+/// Where a step ended up in this run
+enum StepResult {
+    Completed(StepOutput),
+    Failed,
+    Skipped,
+}
+
+pub struct BundleService {
+    storage: Arc<WalletStorage>,
+    detector: Mutex<AnomalyDetector>,
+    /// `None` blocks whatever the detector flags
+    flags: Option<Arc<FeatureFlags>>,
+}
+
+impl BundleService {
+    pub fn new(storage: Arc<WalletStorage>) -> Self {
+        Self { storage, detector: Mutex::new(AnomalyDetector::new()), flags: None }
+    }
+
+    pub fn with_detector(mut self, detector: AnomalyDetector) -> Self {
+        self.detector = Mutex::new(detector);
+        self
+    }
+
+    /// Lets [`Flag::AnomalyBlocking`] turn blocking off per wallet.
+    pub fn with_flags(mut self, flags: Arc<FeatureFlags>) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    /// Runs every counterparty through anomaly detection, scored against the
+    /// bundle's total native outflow on that network rather than the single
+    /// step's amount. The scores, the rule-set hash and the flag state used
+    /// are recorded in `decision`.
+    pub async fn screen(&self, bundle: &Bundle, decision: &mut DecisionContext) -> Result<(), BundleRunError> {
+        let blocking = match &self.flags {
+            Some(flags) => flags.is_enabled(Flag::AnomalyBlocking, Some(&bundle.wallet_name)).await,
+            None => true,
+        };
+        decision.record_flag(Flag::AnomalyBlocking.name(), blocking);
+        let outflow = bundle.native_outflow();
+        let mut detector = self.detector.lock().await;
+        let rules_hash = detector.rules_hash();
+        for (step, network, address, is_contract) in bundle.counterparties() {
+            let total = outflow.get(network).and_then(|d| d.to_f64()).unwrap_or_default();
+            let result = detector.detect_transaction(&format!("{:?}", address), total, None, is_contract);
+            match detector.verdict(&result) {
+                Ok(_) => {}
+                Err(e) if blocking => {
+                    return Err(BundleRunError::Blocked { step: step.to_string(), reason: e.to_string() })
+                }
+                Err(e) => {
+                    warn!("bundle step {} of {} not blocked (anomaly_blocking off): {}", step, bundle.wallet_name, e)
+                }
+            }
+            decision.record_anomaly(&result, &rules_hash);
+        }
+        Ok(())
+    }
+
+    /// Hash of the anomaly rules [`Self::screen`] applies.
+    pub async fn rules_hash(&self) -> String {
+        self.detector.lock().await.rules_hash()
+    }
+
+    /// Hot-reloads the anomaly rules; later screenings record the new hash.
+    pub async fn update_rules(&self, config: AnomalyDetectionConfig) -> Result<(), String> {
+        self.detector.lock().await.update_config(config)
+    }
+
+    /// Stores `bundle` as `pending` and returns its id.
+    pub async fn submit(
+        &self,
+        bundle: &Bundle,
+        requested_by: &str,
+        token_id: Option<&str>,
+    ) -> Result<String, BundleRunError> {
+        let steps = bundle
+            .steps
+            .iter()
+            .map(|step| {
+                Ok(NewBundleStep {
+                    step_id: &step.id,
+                    kind: step.action.kind(),
+                    depends_on: &step.depends_on,
+                    definition: serde_json::to_value(&step.action)?,
+                })
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>()
+            .map_err(|e| BundleRunError::Storage(e.into()))?;
+        let id = self
+            .storage
+            .create_bundle(&NewBundle {
+                wallet_name: &bundle.wallet_name,
+                failure_policy: bundle.failure_policy.as_str(),
+                requested_by,
+                token_id,
+                steps,
+            })
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<BundleRecord, BundleRunError> {
+        self.storage.bundle(id).await?.ok_or(BundleRunError::NotFound)
+    }
+
+    /// Runs a freshly submitted bundle to the end.
+    pub async fn run(&self, id: &str, executor: &dyn StepExecutor) -> Result<BundleRecord, BundleRunError> {
+        self.drive(id, BUNDLE_PENDING, executor).await
+    }
+
+    /// Continues an `interrupted` bundle from its first unfinished step.
+    pub async fn resume(&self, id: &str, executor: &dyn StepExecutor) -> Result<BundleRecord, BundleRunError> {
+        self.drive(id, BUNDLE_INTERRUPTED, executor).await
+    }
+
+    async fn drive(
+        &self,
+        id: &str,
+        from: &str,
+        executor: &dyn StepExecutor,
+    ) -> Result<BundleRecord, BundleRunError> {
+        let record = self.get(id).await?;
+        // claiming the bundle is what keeps two runners off the same steps
+        if !self.storage.transition_bundle(id, from, BUNDLE_RUNNING).await? {
+            return Err(BundleRunError::NotRunnable(self.get(id).await?.status));
+        }
+        let policy: FailurePolicy = record.failure_policy.parse().map_err(corrupt)?;
+
+        let mut outputs: HashMap<String, StepOutput> = HashMap::new();
+        // failed or skipped: steps depending on these are skipped
+        let mut unfinished: HashSet<String> = HashSet::new();
+        let mut aborted_by: Option<String> = None;
+        let mut completed = 0;
+        for step in &record.steps {
+            let result = match step.status.as_str() {
+                STEP_COMPLETED => {
+                    let output = step.output.clone().map(serde_json::from_value).transpose().map_err(corrupt)?;
+                    StepResult::Completed(output.unwrap_or_default())
+                }
+                STEP_FAILED => StepResult::Failed,
+                STEP_SKIPPED => StepResult::Skipped,
+                _ => self.advance(id, step, &outputs, &unfinished, aborted_by.as_deref(), executor).await?,
+            };
+            match result {
+                StepResult::Completed(output) => {
+                    outputs.insert(step.step_id.clone(), output);
+                    completed += 1;
+                }
+                StepResult::Failed => {
+                    unfinished.insert(step.step_id.clone());
+                    if policy == FailurePolicy::AbortRemaining && aborted_by.is_none() {
+                        aborted_by = Some(step.step_id.clone());
+                    }
+                }
+                StepResult::Skipped => {
+                    unfinished.insert(step.step_id.clone());
+                }
+            }
+        }
+
+        let status = if completed == record.steps.len() {
+            BUNDLE_COMPLETED
+        } else if policy == FailurePolicy::Continue && completed > 0 {
+            BUNDLE_PARTIALLY_COMPLETED
+        } else {
+            BUNDLE_FAILED
+        };
+        if !self.storage.transition_bundle(id, BUNDLE_RUNNING, status).await? {
+            return Err(BundleRunError::Conflict(id.to_string()));
+        }
+        info!("bundle {} finished {}: {}/{} steps completed", id, status, completed, record.steps.len());
+        self.get(id).await
+    }
+
+    /// Takes one `pending` (or, after a restart, `running`) step to a final
+    /// status.
+    async fn advance(
+        &self,
+        bundle_id: &str,
+        step: &BundleStepRecord,
+        outputs: &HashMap<String, StepOutput>,
+        unfinished: &HashSet<String>,
+        aborted_by: Option<&str>,
+        executor: &dyn StepExecutor,
+    ) -> Result<StepResult, BundleRunError> {
+        if let Some(failed) = aborted_by {
+            let reason = format!("Step {} failed and the bundle aborts on failure", failed);
+            self.settle(bundle_id, step, STEP_SKIPPED, "BUNDLE_ABORTED", &reason).await?;
+            return Ok(StepResult::Skipped);
+        }
+        if let Some(dependency) = step.depends_on.iter().find(|d| unfinished.contains(*d)) {
+            let reason = format!("Depends on step {}, which did not complete", dependency);
+            self.settle(bundle_id, step, STEP_SKIPPED, "DEPENDENCY_NOT_COMPLETED", &reason).await?;
+            return Ok(StepResult::Skipped);
+        }
+        let action: StepAction = serde_json::from_value(step.definition.clone()).map_err(corrupt)?;
+        if step.status == STEP_RUNNING && !action.retry_safe() {
+            let reason = "Interrupted while executing; check whether it took effect before running it again";
+            self.settle(bundle_id, step, STEP_FAILED, "STEP_OUTCOME_UNKNOWN", reason).await?;
+            return Ok(StepResult::Failed);
+        }
+        let resolved = match action.resolve(outputs) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                self.settle(bundle_id, step, STEP_FAILED, "PLACEHOLDER_UNRESOLVED", &e.to_string()).await?;
+                return Ok(StepResult::Failed);
+            }
+        };
+
+        let resolved_json = serde_json::to_value(&resolved).map_err(corrupt)?;
+        let progress = StepProgress { resolved: Some(&resolved_json), ..Default::default() };
+        self.move_step(bundle_id, step, STEP_RUNNING, &progress).await?;
+        let running = BundleStepRecord { status: STEP_RUNNING.to_string(), ..step.clone() };
+
+        match executor.execute(&step.step_id, &resolved).await {
+            Ok(output) => {
+                let output_json = serde_json::to_value(&output).map_err(corrupt)?;
+                let progress = StepProgress { output: Some(&output_json), ..Default::default() };
+                self.move_step(bundle_id, &running, STEP_COMPLETED, &progress).await?;
+                Ok(StepResult::Completed(output))
+            }
+            Err(failure) => {
+                warn!("bundle {} step {} failed: {}", bundle_id, step.step_id, failure);
+                self.settle(bundle_id, &running, STEP_FAILED, &failure.code, &failure.message).await?;
+                Ok(StepResult::Failed)
+            }
+        }
+    }
+
+    async fn settle(
+        &self,
+        bundle_id: &str,
+        step: &BundleStepRecord,
+        to: &str,
+        code: &str,
+        reason: &str,
+    ) -> Result<(), BundleRunError> {
+        let progress = StepProgress { error_code: Some(code), error: Some(reason), ..Default::default() };
+        self.move_step(bundle_id, step, to, &progress).await
+    }
+
+    async fn move_step(
+        &self,
+        bundle_id: &str,
+        step: &BundleStepRecord,
+        to: &str,
+        progress: &StepProgress<'_>,
+    ) -> Result<(), BundleRunError> {
+        if !self.storage.transition_bundle_step(bundle_id, step, to, progress).await? {
+            return Err(BundleRunError::Conflict(bundle_id.to_string()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+//! Amounts that refer to an earlier step's output.
+//!
+//! An amount field holds either a decimal literal or the placeholder
+//! `{{steps.<id>.amount}}`, which stands for the amount step `<id>` actually
+//! produced (what a swap received, what a bridge delivered). Placeholders are
+//! substituted right before the step runs, so a bridge forwards the swap's
+//! real output rather than the quoted one.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use super::StepOutput;
+
+const OPEN: &str = "{{";
+const CLOSE: &str = "}}";
+/// Output fields a placeholder can name
+const AMOUNT_FIELD: &str = "amount";
+/// Decimal places kept by EVM amounts
+pub const MAX_DECIMALS: u32 = 18;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PlaceholderError {
+    #[error("Malformed placeholder {0:?} (expected {{{{steps.<id>.amount}}}})")]
+    Malformed(String),
+    #[error("Placeholder {0:?} names an unknown output field (only amount is available)")]
+    UnknownField(String),
+    #[error("Invalid amount {0:?}: expected a positive decimal with at most 18 decimal places")]
+    InvalidAmount(String),
+    #[error("Step {0} has not produced an output yet")]
+    MissingOutput(String),
+    #[error("Step {0} produced no amount")]
+    NoAmount(String),
+    #[error("Amount refers to step {0} and has not been resolved")]
+    Unresolved(String),
+}
+
+/// An amount field of a bundle step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmountSpec {
+    /// Decimal whole units
+    Literal(Decimal),
+    /// The amount produced by the named step
+    Output(String),
+}
+
+impl AmountSpec {
+    pub fn literal(amount: Decimal) -> Self {
+        AmountSpec::Literal(amount)
+    }
+
+    pub fn output(step: impl Into<String>) -> Self {
+        AmountSpec::Output(step.into())
+    }
+
+    /// Parses a literal or a placeholder; literals must be positive and keep
+    /// at most 18 decimal places.
+    pub fn parse(text: &str) -> Result<Self, PlaceholderError> {
+        let text = text.trim();
+        if let Some(step) = parse_placeholder(text)? {
+            return Ok(AmountSpec::Output(step));
+        }
+        parse_literal(text).map(AmountSpec::Literal)
+    }
+
+    /// The step this amount refers to, if it is a placeholder
+    pub fn source(&self) -> Option<&str> {
+        match self {
+            AmountSpec::Literal(_) => None,
+            AmountSpec::Output(step) => Some(step),
+        }
+    }
+
+    /// The literal value; placeholders must have been substituted first.
+    pub fn value(&self) -> Result<Decimal, PlaceholderError> {
+        match self {
+            AmountSpec::Literal(amount) => Ok(*amount),
+            AmountSpec::Output(step) => Err(PlaceholderError::Unresolved(step.clone())),
+        }
+    }
+
+    /// Replaces a placeholder with the amount its step produced.
+    pub fn substitute(&self, outputs: &HashMap<String, StepOutput>) -> Result<Self, PlaceholderError> {
+        let AmountSpec::Output(step) = self else {
+            return Ok(self.clone());
+        };
+        let output = outputs.get(step).ok_or_else(|| PlaceholderError::MissingOutput(step.clone()))?;
+        let amount = output.amount.as_deref().ok_or_else(|| PlaceholderError::NoAmount(step.clone()))?;
+        let amount = parse_literal(amount)?;
+        Ok(AmountSpec::Literal(amount))
+    }
+}
+
+impl std::fmt::Display for AmountSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AmountSpec::Literal(amount) => write!(f, "{}", amount.normalize()),
+            AmountSpec::Output(step) => write!(f, "{}steps.{}.{}{}", OPEN, step, AMOUNT_FIELD, CLOSE),
+        }
+    }
+}
+
+impl Serialize for AmountSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AmountSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        AmountSpec::parse(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// `Some(step id)` for `{{steps.<id>.amount}}`, `None` for anything that is
+/// not a placeholder at all.
+fn parse_placeholder(text: &str) -> Result<Option<String>, PlaceholderError> {
+    if !text.contains(OPEN) && !text.contains(CLOSE) {
+        return Ok(None);
+    }
+    let malformed = || PlaceholderError::Malformed(text.to_string());
+    let inner = text.strip_prefix(OPEN).and_then(|t| t.strip_suffix(CLOSE)).ok_or_else(malformed)?;
+    let mut parts = inner.trim().split('.');
+    let (Some("steps"), Some(step), Some(field), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(malformed());
+    };
+    if step.is_empty() {
+        return Err(malformed());
+    }
+    if field != AMOUNT_FIELD {
+        return Err(PlaceholderError::UnknownField(text.to_string()));
+    }
+    Ok(Some(step.to_string()))
+}
+
+fn parse_literal(text: &str) -> Result<Decimal, PlaceholderError> {
+    let invalid = || PlaceholderError::InvalidAmount(text.to_string());
+    // digits and one optional point: no signs, exponents or separators
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return Err(invalid());
+    }
+    let amount = Decimal::from_str(text).map_err(|_| invalid())?;
+    if amount <= Decimal::ZERO || amount.normalize().scale() > MAX_DECIMALS {
+        return Err(invalid());
+    }
+    Ok(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(amount: Option<&str>) -> StepOutput {
+        StepOutput { amount: amount.map(str::to_string), ..Default::default() }
+    }
+
+    #[test]
+    fn test_parse_literals_and_placeholders() {
+        assert_eq!(AmountSpec::parse("1.5").unwrap(), AmountSpec::Literal(Decimal::new(15, 1)));
+        assert_eq!(AmountSpec::parse("{{steps.swap.amount}}").unwrap(), AmountSpec::output("swap"));
+        assert_eq!(AmountSpec::parse(" {{ steps.swap.amount }} ").unwrap(), AmountSpec::output("swap"));
+
+        for bad in ["0", "-1", "1e3", "", "0.0000000000000000001", "1,000"] {
+            assert!(matches!(AmountSpec::parse(bad), Err(PlaceholderError::InvalidAmount(_))), "{}", bad);
+        }
+        for bad in ["{{steps.swap}}", "{{swap.amount}}", "{{steps..amount}}", "x{{steps.swap.amount}}"] {
+            assert!(matches!(AmountSpec::parse(bad), Err(PlaceholderError::Malformed(_))), "{}", bad);
+        }
+        assert!(matches!(AmountSpec::parse("{{steps.swap.tx_hash}}"), Err(PlaceholderError::UnknownField(_))));
+    }
+
+    #[test]
+    fn test_round_trips_through_json() {
+        for text in ["0.285714285714285714", "{{steps.swap.amount}}"] {
+            let spec: AmountSpec = serde_json::from_value(serde_json::json!(text)).unwrap();
+            assert_eq!(serde_json::to_value(&spec).unwrap(), serde_json::json!(text));
+        }
+    }
+
+    #[test]
+    fn test_substitute_uses_the_produced_amount() {
+        let outputs = HashMap::from([
+            ("swap".to_string(), output(Some("0.285714285714285714"))),
+            ("approve".to_string(), output(None)),
+        ]);
+        let resolved = AmountSpec::output("swap").substitute(&outputs).unwrap();
+        assert_eq!(resolved.to_string(), "0.285714285714285714");
+        assert_eq!(resolved.value().unwrap(), Decimal::from_str("0.285714285714285714").unwrap());
+
+        let literal = AmountSpec::parse("2").unwrap();
+        assert_eq!(literal.substitute(&outputs).unwrap(), literal);
+        assert_eq!(
+            AmountSpec::output("approve").substitute(&outputs),
+            Err(PlaceholderError::NoAmount("approve".to_string()))
+        );
+        assert_eq!(
+            AmountSpec::output("bridge").substitute(&outputs),
+            Err(PlaceholderError::MissingOutput("bridge".to_string()))
+        );
+        assert!(AmountSpec::output("swap").value().is_err());
+    }
+}
@@ -0,0 +1,366 @@
+//! Backfill of the on-chain history of imported wallets.
+//!
+//! A backfill is requested per wallet and network: the wallet's address on
+//! that network gets a scan cursor from the requested block to the current
+//! head. [`HistoryBackfill`] then reads the range in batches of
+//! `blocks_per_batch` from the network's [`HistorySource`] (the explorer API
+//! when one is configured, block scanning otherwise), turns every transfer
+//! into a ledger entry with [`normalize`] and stores each batch together with
+//! the cursor advance. A cancelled or failed run leaves the cursor at the
+//! first block not stored, and the next run picks it up from there.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ethers::types::{Address, U256};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::blockchain::client_registry::ClientRegistry;
+use crate::blockchain::history::{ExplorerHistory, HistorySource, RawTransfer, RpcHistory};
+use crate::blockchain::rpc_limits::{endpoint_label, EndpointLimiter};
+use crate::core::config::BackfillConfig;
+use crate::core::errors::WalletError;
+use crate::ops::jobs::{Job, Schedule};
+use crate::ops::leases::LeaderLease;
+use crate::monitoring::WalletMetrics;
+use crate::security::error_sanitizer::sanitize_error_message;
+use crate::storage::{
+    NewLedgerEntry, NewScanRequest, ScanCursor, WalletStorage, DIRECTION_IN, DIRECTION_OUT, DIRECTION_SELF,
+    NATIVE_TOKEN, SCAN_COMPLETE, TX_ENTRY_INDEX,
+};
+
+/// Scheduler lease job name
+pub const BACKFILL_JOB: &str = "history_backfill";
+
+#[derive(Debug, thiserror::Error)]
+pub enum BackfillError {
+    /// The wallet has no address on the network
+    #[error("wallet {0} is not registered on {1}")]
+    NotRegistered(String, String),
+    #[error(transparent)]
+    Chain(#[from] WalletError),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+    #[error("backfill cancelled")]
+    Cancelled,
+}
+
+/// Outcome of one pass over the running backfills.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillReport {
+    pub batches: usize,
+    /// New ledger entries
+    pub entries: usize,
+    /// Cursors that reached their target
+    pub completed: usize,
+    /// Batches that failed; their cursors wait for the next run
+    pub errors: usize,
+    pub cancelled: bool,
+}
+
+/// The ledger entry for `transfer` as seen by `address`.
+///
+/// The sender pays the fee, so a transaction sent by the address always
+/// yields an entry, even when it moved no native value (token transfers,
+/// contract calls). Failed transactions are kept with `success = false`;
+/// only their fee counts.
+pub fn normalize(address: Address, transfer: &RawTransfer) -> NewLedgerEntry {
+    let sent = transfer.from == address;
+    let received = transfer.to == Some(address);
+    let (direction, counterparty) = match (sent, received) {
+        (true, true) => (DIRECTION_SELF, Some(address)),
+        (true, false) => (DIRECTION_OUT, transfer.to),
+        _ => (DIRECTION_IN, Some(transfer.from)),
+    };
+    // token logs carry no gas of their own; the transaction entry has it
+    let fee = if sent && transfer.log_index.is_none() { transfer.fee } else { U256::zero() };
+    NewLedgerEntry {
+        tx_hash: format!("{:?}", transfer.tx_hash),
+        log_index: transfer.log_index.map_or(TX_ENTRY_INDEX, |i| i as i64),
+        block_number: transfer.block_number as i64,
+        direction,
+        token: transfer.token.map_or_else(|| NATIVE_TOKEN.to_string(), |t| format!("{:?}", t)),
+        counterparty: counterparty.map(|a| format!("{:?}", a)),
+        amount: transfer.value,
+        fee,
+        success: transfer.success,
+        occurred_at: transfer.timestamp,
+    }
+}
+
+pub struct HistoryBackfill {
+    config: BackfillConfig,
+    storage: Arc<WalletStorage>,
+    /// Used for networks without an entry in `sources`
+    default_source: Arc<dyn HistorySource>,
+    sources: HashMap<String, Arc<dyn HistorySource>>,
+    lease: Option<LeaderLease>,
+}
+
+impl HistoryBackfill {
+    pub fn new(config: BackfillConfig, storage: Arc<WalletStorage>, default_source: Arc<dyn HistorySource>) -> Self {
+        Self { config, storage, default_source, sources: HashMap::new(), lease: None }
+    }
+
+    /// Block scanning through `chain_clients`, except for the networks with
+    /// an explorer API under `config.explorers`.
+    pub fn from_config(
+        config: &BackfillConfig,
+        storage: Arc<WalletStorage>,
+        chain_clients: Arc<ClientRegistry>,
+        metrics: Arc<WalletMetrics>,
+    ) -> Self {
+        let mut backfill = Self::new(config.clone(), storage, Arc::new(RpcHistory::new(chain_clients)));
+        for (network, explorer) in &config.explorers {
+            let api_key = explorer.api_key_env.as_deref().and_then(|var| std::env::var(var).ok());
+            if let (Some(var), None) = (&explorer.api_key_env, &api_key) {
+                warn!("backfill explorer for {}: {} is not set, requesting without a key", network, var);
+            }
+            let limiter = explorer.rate_limit.as_ref().map(|limit| {
+                Arc::new(EndpointLimiter::new(endpoint_label(&explorer.url), limit).with_metrics(metrics.clone()))
+            });
+            let source = ExplorerHistory::new(&explorer.url, api_key, limiter);
+            backfill = backfill.with_source(network.clone(), Arc::new(source));
+        }
+        backfill
+    }
+
+    /// Reads `network` from `source` instead of the default.
+    pub fn with_source(mut self, network: impl Into<String>, source: Arc<dyn HistorySource>) -> Self {
+        self.sources.insert(network.into(), source);
+        self
+    }
+
+    /// Only tick on the instance holding `lease`.
+    pub fn with_lease(mut self, lease: LeaderLease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.poll_interval_secs.max(1))
+    }
+
+    /// Where `network` is read from
+    pub fn source(&self, network: &str) -> &Arc<dyn HistorySource> {
+        self.sources.get(network).unwrap_or(&self.default_source)
+    }
+
+    /// Starts or extends the backfill of `wallet_name` on `network` from
+    /// `from_block` (genesis when `None`) to the current head.
+    pub async fn request(
+        &self,
+        wallet_name: &str,
+        network: &str,
+        from_block: Option<u64>,
+    ) -> Result<ScanCursor, BackfillError> {
+        let address = self
+            .storage
+            .wallet_networks(wallet_name)
+            .await?
+            .into_iter()
+            .find(|row| row.network == network)
+            .map(|row| row.address)
+            .ok_or_else(|| BackfillError::NotRegistered(wallet_name.to_string(), network.to_string()))?;
+        let source = self.source(network);
+        let head = source.head(network).await?;
+        let cursor = self
+            .storage
+            .request_backfill(&NewScanRequest {
+                wallet_name,
+                network,
+                address: &address,
+                from_block: from_block.unwrap_or(0) as i64,
+                target_block: head as i64,
+                source: source.strategy(),
+            })
+            .await?;
+        info!(
+            "backfill of {} on {} requested: blocks {}..={} (next {})",
+            wallet_name, network, cursor.from_block, cursor.target_block, cursor.next_block
+        );
+        Ok(cursor)
+    }
+
+    /// Reads and stores the next batch of `cursor`; returns the new entries.
+    async fn batch(&self, cursor: &ScanCursor) -> Result<usize, BackfillError> {
+        let address: Address = cursor
+            .address
+            .parse()
+            .map_err(|_| WalletError::InvalidAddress(format!("scan cursor address {}", cursor.address)))?;
+        let from = cursor.next_block as u64;
+        let to = (from + self.config.blocks_per_batch.max(1) - 1).min(cursor.target_block as u64);
+        let transfers = self.source(&cursor.network).transfers(&cursor.network, address, from, to).await?;
+        let entries: Vec<NewLedgerEntry> = transfers.iter().map(|t| normalize(address, t)).collect();
+        let inserted = self.storage.store_backfill_batch(cursor, &entries, to).await?;
+        debug!(
+            "backfill of {} on {}: blocks {}..={} read, {} of {} entries new",
+            cursor.wallet_name,
+            cursor.network,
+            from,
+            to,
+            inserted,
+            entries.len()
+        );
+        Ok(inserted)
+    }
+
+    /// Runs the backfill of `wallet_name` on `network` to its target in the
+    /// foreground, stopping at the first error.
+    pub async fn run_to_completion(
+        &self,
+        wallet_name: &str,
+        network: &str,
+        cancel: CancellationToken,
+    ) -> Result<ScanCursor, BackfillError> {
+        loop {
+            let cursor = self
+                .storage
+                .backfill_cursors(wallet_name)
+                .await?
+                .into_iter()
+                .find(|c| c.network == network)
+                .ok_or_else(|| BackfillError::NotRegistered(wallet_name.to_string(), network.to_string()))?;
+            if cursor.status == SCAN_COMPLETE {
+                return Ok(cursor);
+            }
+            if cancel.is_cancelled() {
+                return Err(BackfillError::Cancelled);
+            }
+            if let Err(e) = self.batch(&cursor).await {
+                self.note_failure(&cursor, &e).await;
+                return Err(e);
+            }
+        }
+    }
+
+    async fn note_failure(&self, cursor: &ScanCursor, e: &BackfillError) {
+        let message = sanitize_error_message(&e.to_string());
+        warn!(
+            "backfill of {} on {} failed at block {}: {}",
+            cursor.wallet_name, cursor.network, cursor.next_block, message
+        );
+        if let Err(e) = self.storage.record_backfill_error(cursor, &message).await {
+            warn!("failed to record backfill error: {}", e);
+        }
+    }
+
+    /// Advances every running backfill one batch at a time, round-robin,
+    /// until all are complete or `cancel` fires. A cursor whose batch fails
+    /// is left alone for the rest of the pass.
+    pub async fn run_once(&self, cancel: &CancellationToken) -> BackfillReport {
+        let mut report = BackfillReport::default();
+        let mut failed = HashSet::new();
+        loop {
+            let cursors = match self.storage.running_backfills().await {
+                Ok(cursors) => cursors,
+                Err(e) => {
+                    warn!("backfill: failed to list scan cursors: {}", e);
+                    report.errors += 1;
+                    return report;
+                }
+            };
+            let mut progressed = false;
+            for cursor in cursors {
+                let key = (cursor.wallet_name.clone(), cursor.network.clone(), cursor.address.clone());
+                if failed.contains(&key) {
+                    continue;
+                }
+                if cancel.is_cancelled() {
+                    report.cancelled = true;
+                    return report;
+                }
+                match self.batch(&cursor).await {
+                    Ok(inserted) => {
+                        progressed = true;
+                        report.batches += 1;
+                        report.entries += inserted;
+                        let through = cursor.next_block + self.config.blocks_per_batch.max(1) as i64 - 1;
+                        if through >= cursor.target_block {
+                            report.completed += 1;
+                        }
+                    }
+                    // waiting for quota is what got cancelled; that is not the cursor's fault
+                    Err(_) if cancel.is_cancelled() => {
+                        report.cancelled = true;
+                        return report;
+                    }
+                    Err(e) => {
+                        self.note_failure(&cursor, &e).await;
+                        report.errors += 1;
+                        failed.insert(key);
+                    }
+                }
+            }
+            if !progressed {
+                return report;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Job for HistoryBackfill {
+    fn name(&self) -> &str {
+        BACKFILL_JOB
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: self.interval(), immediate: true }
+    }
+
+    fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// Fails when every batch attempted failed.
+    async fn run(&self, cancel: CancellationToken) -> anyhow::Result<()> {
+        let report = self.run_once(&cancel).await;
+        debug!("backfill cycle: {:?}", report);
+        if report.errors > 0 && report.batches == 0 {
+            anyhow::bail!("backfill cycle failed ({} errors)", report.errors);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+
+    #[test]
+    fn test_normalize_charges_the_fee_to_the_sender_only() {
+        let wallet = Address::from_low_u64_be(0xaa);
+        let other = Address::from_low_u64_be(0xbb);
+        let token = Address::from_low_u64_be(0xcc);
+        let tx = RawTransfer {
+            tx_hash: H256::from_low_u64_be(1),
+            log_index: None,
+            block_number: 7,
+            timestamp: 1_700_000_000,
+            from: wallet,
+            to: Some(token),
+            token: None,
+            value: U256::zero(),
+            fee: U256::from(21_000u64),
+            success: true,
+        };
+        let entry = normalize(wallet, &tx);
+        assert_eq!((entry.direction, entry.log_index, entry.fee), (DIRECTION_OUT, TX_ENTRY_INDEX, tx.fee));
+        assert_eq!(entry.token, NATIVE_TOKEN);
+
+        // the token log of the same transaction: received by `other`, no fee of its own
+        let log = RawTransfer { log_index: Some(3), from: wallet, to: Some(other), token: Some(token), ..tx.clone() };
+        let entry = normalize(wallet, &log);
+        assert_eq!((entry.direction, entry.fee), (DIRECTION_OUT, U256::zero()));
+        assert_eq!(entry.token, format!("{:?}", token));
+        assert_eq!(normalize(other, &log).direction, DIRECTION_IN);
+
+        let to_self = RawTransfer { to: Some(wallet), ..tx };
+        assert_eq!(normalize(wallet, &to_self).direction, DIRECTION_SELF);
+    }
+}
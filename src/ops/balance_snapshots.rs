@@ -0,0 +1,339 @@
+//! src/ops/balance_snapshots.rs
+//!
+//! Background job that records each linked wallet's native balance per
+//! network for the balance-history charts.
+//!
+//! One cycle queries every (address, network) pair once, even when several
+//! wallets share an address, and writes a row only when the balance moved.
+//! Every fetched balance is also fed to the wallets' balance subscriptions.
+//! Failures are logged and counted; they never stop the loop.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::api::user_db::UserDatabase;
+use crate::cli::amounts::{from_decimal, Denomination};
+use crate::blockchain::circuit_breaker::CircuitBreaker;
+use crate::blockchain::client_registry::ClientRegistry;
+use crate::core::config::BalanceSnapshotConfig;
+use crate::monitoring::WalletMetrics;
+use crate::ops::jobs::{Job, Schedule};
+use crate::ops::leases::LeaderLease;
+use crate::ops::maintenance::MaintenanceMode;
+use crate::pricing::{native_symbol, PriceFeed};
+use crate::storage::{BalanceObservation, BalanceSnapshot, WalletStorage, NATIVE_TOKEN};
+
+/// Outcome of one snapshot cycle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotCycleReport {
+    /// Rows written (balance changed)
+    pub recorded: usize,
+    /// Balances fetched but identical to the previous snapshot
+    pub unchanged: usize,
+    /// Balance fetches or writes that failed
+    pub failed: usize,
+    /// Networks skipped because their circuit breaker was open
+    pub skipped_networks: Vec<String>,
+    /// Rows removed by downsampling
+    pub downsampled: u64,
+    /// The whole cycle was skipped (maintenance mode)
+    pub skipped: bool,
+    /// Native coin prices recorded for `fiat_value_at_time`
+    pub prices_recorded: usize,
+    /// `balance.changed` events emitted for subscriptions
+    pub notifications: usize,
+}
+
+pub struct BalanceSnapshotter {
+    config: BalanceSnapshotConfig,
+    user_db: Arc<UserDatabase>,
+    storage: Arc<WalletStorage>,
+    chain_clients: Arc<ClientRegistry>,
+    maintenance: Arc<MaintenanceMode>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    metrics: Arc<WalletMetrics>,
+    lease: Option<LeaderLease>,
+    /// Feed and quote currency of the price snapshots taken each cycle
+    pricing: Option<(Arc<dyn PriceFeed>, String)>,
+}
+
+/// Scheduler lease job name
+pub const BALANCE_SNAPSHOTS_JOB: &str = "balance_snapshots";
+
+impl BalanceSnapshotter {
+    pub fn new(
+        config: BalanceSnapshotConfig,
+        user_db: Arc<UserDatabase>,
+        storage: Arc<WalletStorage>,
+        chain_clients: Arc<ClientRegistry>,
+        maintenance: Arc<MaintenanceMode>,
+        circuit_breaker: Arc<CircuitBreaker>,
+        metrics: Arc<WalletMetrics>,
+    ) -> Self {
+        Self {
+            config,
+            user_db,
+            storage,
+            chain_clients,
+            maintenance,
+            circuit_breaker,
+            metrics,
+            lease: None,
+            pricing: None,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs.max(1))
+    }
+
+    /// Only tick on the instance holding `lease`.
+    pub fn with_lease(mut self, lease: LeaderLease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    /// Also record each network's native coin price in `currency` per cycle.
+    pub fn with_pricing(mut self, feed: Arc<dyn PriceFeed>, currency: &str) -> Self {
+        self.pricing = Some((feed, currency.to_ascii_lowercase()));
+        self
+    }
+
+    pub fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    fn networks(&self) -> Vec<String> {
+        let configured = self.chain_clients.networks();
+        if self.config.networks.is_empty() {
+            configured
+        } else {
+            configured.into_iter().filter(|n| self.config.networks.contains(n)).collect()
+        }
+    }
+
+    /// One snapshot pass over all linked wallets and networks.
+    pub async fn run_once(&self) -> SnapshotCycleReport {
+        let mut report = SnapshotCycleReport::default();
+        if self.maintenance.is_enabled() {
+            debug!("balance snapshots skipped: maintenance mode");
+            report.skipped = true;
+            return report;
+        }
+
+        let wallets = match self.user_db.list_wallet_addresses().await {
+            Ok(w) => w,
+            Err(e) => {
+                warn!("balance snapshots: failed to list wallets: {}", e);
+                self.metrics.record_balance_snapshot_failure();
+                report.failed += 1;
+                return report;
+            }
+        };
+
+        // address -> wallet ids sharing it; queried once per network
+        let mut by_address: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (id, address) in wallets {
+            by_address.entry(address.to_lowercase()).or_default().push(id.to_string());
+        }
+
+        let captured_at = Utc::now().timestamp();
+        let per_network = futures::future::join_all(
+            self.networks().into_iter().map(|network| self.snapshot_network(network, &by_address, captured_at)),
+        )
+        .await;
+        for network_report in per_network {
+            report.recorded += network_report.recorded;
+            report.unchanged += network_report.unchanged;
+            report.failed += network_report.failed;
+            report.notifications += network_report.notifications;
+            report.skipped_networks.extend(network_report.skipped_networks);
+        }
+        if let Some((feed, currency)) = &self.pricing {
+            report.prices_recorded = self.snapshot_prices(feed.as_ref(), currency, captured_at).await;
+        }
+
+        let cutoff = Utc::now() - chrono::Duration::days(i64::from(self.config.hourly_retention_days));
+        match self.storage.downsample_balance_snapshots(cutoff).await {
+            Ok(n) => report.downsampled = n,
+            Err(e) => {
+                warn!("balance snapshots: downsampling failed: {}", e);
+                self.metrics.record_balance_snapshot_failure();
+                report.failed += 1;
+            }
+        }
+
+        if report.recorded > 0 || report.failed > 0 {
+            info!(
+                "balance snapshots: {} recorded, {} unchanged, {} failed",
+                report.recorded, report.unchanged, report.failed
+            );
+        }
+        report
+    }
+
+    /// Prices are best-effort: a failed lookup only leaves a gap in the
+    /// history's `fiat_value_at_time`.
+    async fn snapshot_prices(&self, feed: &dyn PriceFeed, currency: &str, captured_at: i64) -> usize {
+        let mut coins: Vec<&str> = self.networks().iter().filter_map(|n| native_symbol(n)).collect();
+        coins.sort_unstable();
+        coins.dedup();
+        let mut recorded = 0;
+        for coin in coins {
+            let price = match feed.get_price(coin, currency).await {
+                Ok(price) => price.price,
+                Err(e) => {
+                    warn!("balance snapshots: no {} price in {}: {}", coin, currency, e);
+                    continue;
+                }
+            };
+            match self.storage.record_price_snapshot(coin, currency, &price.to_string(), captured_at).await {
+                Ok(()) => recorded += 1,
+                Err(e) => warn!("balance snapshots: failed to store {} price: {}", coin, e),
+            }
+        }
+        recorded
+    }
+
+    /// Two samples one interval apart can't belong to the same burst, so
+    /// the window only applies when snapshots run more often than it lasts;
+    /// otherwise a change would wait a whole extra cycle to be reported.
+    fn subscription_debounce_secs(&self) -> u64 {
+        let debounce = self.config.subscription_debounce_secs;
+        if self.interval().as_secs() >= debounce {
+            0
+        } else {
+            debounce
+        }
+    }
+
+    async fn notify_subscribers(
+        &self,
+        wallet_id: &str,
+        network: &str,
+        denom: &Denomination,
+        balance: &str,
+    ) -> Result<usize, String> {
+        let wallet_id: i64 = wallet_id.parse().map_err(|_| format!("invalid wallet id {}", wallet_id))?;
+        let balance = from_decimal(balance, denom).map_err(|e| format!("unparsable balance {}: {}", balance, e))?;
+        let observation = BalanceObservation { wallet_id, network, token: NATIVE_TOKEN, balance, tx_hash: None };
+        self.storage
+            .observe_balance(&observation, self.subscription_debounce_secs())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn snapshot_network(
+        &self,
+        network: String,
+        by_address: &BTreeMap<String, Vec<String>>,
+        captured_at: i64,
+    ) -> SnapshotCycleReport {
+        let mut report = SnapshotCycleReport::default();
+        if !self.circuit_breaker.allow(&network) {
+            report.skipped_networks.push(network);
+            return report;
+        }
+        let client = match self.chain_clients.get(&network) {
+            Ok(c) => c,
+            Err(e) => {
+                warn!("balance snapshots: {}", e);
+                report.failed += 1;
+                return report;
+            }
+        };
+        let block_number = client.get_block_number().await.ok().map(|b| b as i64);
+        // 无法换算到最小单位的network不评估订阅
+        let denom = Denomination::for_network(&network);
+
+        // wallets on other chain families are not this client's business; owned so
+        // the buffered fetches hold no borrows and the job future stays `Send`
+        let targets: Vec<(String, Vec<String>)> = by_address
+            .iter()
+            .filter(|(address, _)| client.validate_address(address).unwrap_or(false))
+            .map(|(address, ids)| (address.clone(), ids.clone()))
+            .collect();
+
+        let mut fetches = stream::iter(targets)
+            .map(|(address, ids)| {
+                let client = client.clone();
+                async move { (ids, client.get_balance(&address).await) }
+            })
+            .buffer_unordered(self.config.per_network_concurrency.max(1));
+
+        while let Some((ids, result)) = fetches.next().await {
+            let balance = match result {
+                Ok(b) => {
+                    self.circuit_breaker.record_success(&network);
+                    b
+                }
+                Err(e) => {
+                    warn!("balance snapshots: {} balance query failed: {}", network, e);
+                    self.circuit_breaker.record_failure(&network);
+                    self.metrics.record_balance_snapshot_failure();
+                    report.failed += 1;
+                    continue;
+                }
+            };
+            for wallet_id in &ids {
+                let snapshot = BalanceSnapshot {
+                    wallet_id: wallet_id.clone(),
+                    network: network.clone(),
+                    token: NATIVE_TOKEN.to_string(),
+                    balance: balance.clone(),
+                    block_number,
+                    captured_at,
+                };
+                match self.storage.record_balance_snapshot(&snapshot).await {
+                    Ok(true) => report.recorded += 1,
+                    Ok(false) => report.unchanged += 1,
+                    Err(e) => {
+                        warn!("balance snapshots: failed to store snapshot for wallet {}: {}", wallet_id, e);
+                        self.metrics.record_balance_snapshot_failure();
+                        report.failed += 1;
+                    }
+                }
+                // 余额未变也要评估：可能有待发出的防抖窗口
+                let Some(denom) = &denom else { continue };
+                match self.notify_subscribers(wallet_id, &network, denom, &balance).await {
+                    Ok(n) => report.notifications += n,
+                    Err(e) => warn!("balance snapshots: subscriptions of wallet {} not evaluated: {}", wallet_id, e),
+                }
+            }
+        }
+        report
+    }
+}
+
+#[async_trait]
+impl Job for BalanceSnapshotter {
+    fn name(&self) -> &str {
+        BALANCE_SNAPSHOTS_JOB
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: self.interval(), immediate: true }
+    }
+
+    fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// Individual fetch failures are routine; the run fails when nothing
+    /// could be fetched at all.
+    async fn run(&self, _cancel: CancellationToken) -> anyhow::Result<()> {
+        let report = self.run_once().await;
+        debug!("balance snapshot cycle: {:?}", report);
+        if report.failed > 0 && report.recorded + report.unchanged == 0 {
+            anyhow::bail!("balance snapshot cycle failed ({} errors)", report.failed);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,403 @@
+//! src/ops/block_tracking.rs
+//!
+//! Block-driven confirmation tracking for the networks listed under
+//! `fee_tracking.block_tracking`.
+//!
+//! [`ConfirmationPoller`](crate::ops::fee_tracking::ConfirmationPoller) asks
+//! for one receipt per pending send per cycle, which does not scale to
+//! hundreds of sends in flight. [`BlockTracker`] instead follows the chain
+//! head of one network:
+//!
+//! * the pending sends of the network are held in memory, at most
+//!   `max_pending`, and reconciled with `fee_history` every
+//!   `reconcile_interval_secs`. A send seen for the first time gets one
+//!   receipt lookup, which covers blocks mined before it was tracked;
+//! * each new block is read once. Receipts are fetched only for tracked
+//!   hashes in its transaction list, and kept;
+//! * a mined send's confirmations are the height difference to the last
+//!   block read, so no receipt is fetched again while it deepens;
+//! * a block whose parent hash differs from the hash recorded at the height
+//!   below means a reorg: the tracker steps back until the parents agree and
+//!   every send mined at or above the fork is pending again.
+//!
+//! Once a send has the network's required confirmations its stored receipt
+//! is applied exactly as the poller applies one ([`apply_receipt`]), so fee
+//! rows, transaction statuses, events and pending expiry are unchanged.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use ethers::types::{TransactionReceipt, H256};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::core::config::FeeTrackingConfig;
+use crate::core::errors::WalletError;
+use crate::intents::{BroadcastChain, ChainBlock};
+use crate::ops::fee_tracking::apply_receipt;
+use crate::ops::jobs::{Job, Schedule};
+use crate::ops::leases::LeaderLease;
+use crate::storage::{FeeRecord, WalletStorage};
+
+/// Scheduler job name prefix; the job of a network is `block_tracking_<network>`
+pub const BLOCK_TRACKING_JOB: &str = "block_tracking";
+
+pub fn job_name(network: &str) -> String {
+    format!("{}_{}", BLOCK_TRACKING_JOB, network)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockTrackingReport {
+    /// Blocks read, including blocks read again after a reorg
+    pub blocks: usize,
+    /// Sends found mined, in a block or by their first lookup
+    pub included: usize,
+    pub receipts_fetched: usize,
+    /// Applied with status 1
+    pub confirmed: usize,
+    /// Applied but reverted
+    pub reverted: usize,
+    /// Mined sends moved back to pending by a reorg
+    pub reorged: usize,
+    pub errors: usize,
+}
+
+struct Inclusion {
+    height: u64,
+    block_hash: H256,
+    receipt: TransactionReceipt,
+}
+
+struct Tracked {
+    record: FeeRecord,
+    inclusion: Option<Inclusion>,
+    /// Look the receipt up at the next reconciliation: the send is new, or
+    /// its block was read before the node had the receipt
+    lookup: bool,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    sends: HashMap<H256, Tracked>,
+    /// Hashes of the blocks read most recently, by height
+    hashes: BTreeMap<u64, H256>,
+    /// Last block read
+    cursor: Option<u64>,
+    reconciled_at: Option<Instant>,
+}
+
+/// Confirmations of a block at `height` once `cursor` has been read.
+fn depth(cursor: u64, height: u64) -> u64 {
+    cursor.checked_sub(height).map_or(0, |d| d + 1)
+}
+
+pub struct BlockTracker {
+    network: String,
+    name: String,
+    config: FeeTrackingConfig,
+    required_confirmations: u64,
+    storage: Arc<WalletStorage>,
+    chain: Arc<dyn BroadcastChain>,
+    state: Mutex<TrackerState>,
+    lease: Option<LeaderLease>,
+}
+
+impl BlockTracker {
+    pub fn new(
+        network: impl Into<String>,
+        config: FeeTrackingConfig,
+        required_confirmations: u64,
+        storage: Arc<WalletStorage>,
+        chain: Arc<dyn BroadcastChain>,
+    ) -> Self {
+        let network = network.into();
+        Self {
+            name: job_name(&network),
+            network,
+            config,
+            required_confirmations: required_confirmations.max(1),
+            storage,
+            chain,
+            state: Mutex::new(TrackerState::default()),
+            lease: None,
+        }
+    }
+
+    pub fn network(&self) -> &str {
+        &self.network
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.block_tracking.poll_interval_secs.max(1))
+    }
+
+    pub fn with_lease(mut self, lease: LeaderLease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    pub fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// Sends held in memory
+    pub async fn tracked(&self) -> usize {
+        self.state.lock().await.sends.len()
+    }
+
+    /// Confirmations of a tracked send; `None` while it is not mined or no
+    /// longer tracked.
+    pub async fn confirmations(&self, tx_hash: &str) -> Option<u64> {
+        let hash: H256 = tx_hash.parse().ok()?;
+        let state = self.state.lock().await;
+        let inclusion = state.sends.get(&hash)?.inclusion.as_ref()?;
+        Some(depth(state.cursor?, inclusion.height))
+    }
+
+    /// One cycle: reconcile when due, read the blocks up to the head, apply
+    /// the sends that are deep enough.
+    pub async fn run_once(&self) -> BlockTrackingReport {
+        let mut report = BlockTrackingReport::default();
+        let mut guard = self.state.lock().await;
+        let state = &mut *guard;
+        let every = Duration::from_secs(self.config.block_tracking.reconcile_interval_secs);
+        if state.reconciled_at.is_none_or(|at| at.elapsed() >= every) {
+            self.reconcile(state, &mut report).await;
+        }
+        match self.chain.block_number(&self.network).await {
+            Ok(head) => self.follow(state, head, &mut report).await,
+            Err(e) => {
+                warn!("block tracking on {}: failed to get the head: {}", self.network, e);
+                report.errors += 1;
+            }
+        }
+        self.settle(state, &mut report).await;
+        if report.confirmed + report.reverted + report.reorged > 0 {
+            info!(
+                "block tracking on {}: {} confirmed, {} reverted, {} re-queued by reorgs",
+                self.network, report.confirmed, report.reverted, report.reorged
+            );
+        }
+        report
+    }
+
+    /// Replaces the tracked set with the oldest `max_pending` unconfirmed
+    /// sends of the network, keeping what is known about sends still there,
+    /// then looks up the receipts of new ones.
+    async fn reconcile(&self, state: &mut TrackerState, report: &mut BlockTrackingReport) {
+        let since = Utc::now() - chrono::Duration::hours(self.config.confirmation_max_age_hours as i64);
+        let limit = self.config.block_tracking.max_pending as i64;
+        let rows = match self.storage.unconfirmed_fee_records_on(&self.network, since, limit).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!("block tracking on {}: failed to list pending sends: {}", self.network, e);
+                report.errors += 1;
+                return;
+            }
+        };
+        let mut sends = HashMap::with_capacity(rows.len());
+        for record in rows {
+            let Ok(hash) = record.tx_hash.parse::<H256>() else {
+                warn!("block tracking on {}: invalid transaction hash {}", self.network, record.tx_hash);
+                continue;
+            };
+            let tracked = match state.sends.remove(&hash) {
+                Some(tracked) => Tracked { record, ..tracked },
+                None => Tracked { record, inclusion: None, lookup: true },
+            };
+            sends.insert(hash, tracked);
+        }
+        // the rest was applied elsewhere or aged out
+        state.sends = sends;
+        state.reconciled_at = Some(Instant::now());
+
+        let lookups: Vec<H256> =
+            state.sends.iter().filter(|(_, t)| t.lookup && t.inclusion.is_none()).map(|(hash, _)| *hash).collect();
+        for (hash, result) in self.fetch_receipts(&lookups, report).await {
+            let Some(tracked) = state.sends.get_mut(&hash) else {
+                continue;
+            };
+            match result {
+                Ok(Some(receipt)) => {
+                    tracked.lookup = false;
+                    if let (Some(height), Some(block_hash)) = (receipt.block_number, receipt.block_hash) {
+                        tracked.inclusion = Some(Inclusion { height: height.as_u64(), block_hash, receipt });
+                        report.included += 1;
+                    }
+                }
+                // not mined yet: the block that includes it will show it
+                Ok(None) => tracked.lookup = false,
+                Err(e) => {
+                    warn!("block tracking on {}: receipt of {:?}: {}", self.network, hash, e);
+                    report.errors += 1;
+                }
+            }
+        }
+    }
+
+    /// Reads the blocks after the cursor up to `head`, stepping back on a
+    /// parent hash mismatch.
+    async fn follow(&self, state: &mut TrackerState, head: u64, report: &mut BlockTrackingReport) {
+        if state.sends.is_empty() {
+            // nothing to find; a send tracked later gets its first lookup
+            state.cursor = Some(head);
+            state.hashes.clear();
+            return;
+        }
+        let mut next = state.cursor.map_or(head, |c| c + 1);
+        let mut budget = self.config.block_tracking.max_blocks_per_cycle.max(1);
+        while next <= head && budget > 0 {
+            budget -= 1;
+            let block = match self.chain.block(&self.network, next).await {
+                Ok(Some(block)) => block,
+                // the node has not caught up with its own head yet
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("block tracking on {}: block {}: {}", self.network, next, e);
+                    report.errors += 1;
+                    break;
+                }
+            };
+            report.blocks += 1;
+            let parent = next.checked_sub(1).and_then(|p| state.hashes.get(&p));
+            if parent.is_some_and(|hash| *hash != block.parent_hash) {
+                // the block read at `next - 1` is no longer canonical
+                report.reorged += Self::rewind(state, next - 1);
+                next -= 1;
+                continue;
+            }
+            self.read_block(state, &block, report).await;
+            state.hashes.insert(block.number, block.hash);
+            state.cursor = Some(block.number);
+            let keep = self.config.block_tracking.reorg_window.max(self.required_confirmations);
+            let oldest = block.number.saturating_sub(keep);
+            state.hashes.retain(|height, _| *height >= oldest);
+            next += 1;
+        }
+    }
+
+    /// Forgets the blocks from `fork` up; returns the sends moved back to
+    /// pending.
+    fn rewind(state: &mut TrackerState, fork: u64) -> usize {
+        state.hashes.retain(|height, _| *height < fork);
+        state.cursor = fork.checked_sub(1);
+        let mut moved = 0;
+        for tracked in state.sends.values_mut() {
+            if tracked.inclusion.as_ref().is_some_and(|i| i.height >= fork) {
+                tracked.inclusion = None;
+                moved += 1;
+            }
+        }
+        moved
+    }
+
+    async fn read_block(&self, state: &mut TrackerState, block: &ChainBlock, report: &mut BlockTrackingReport) {
+        // inclusions from a first lookup that this block no longer holds
+        for tracked in state.sends.values_mut() {
+            if tracked.inclusion.as_ref().is_some_and(|i| i.height == block.number && i.block_hash != block.hash) {
+                tracked.inclusion = None;
+                report.reorged += 1;
+            }
+        }
+        let ours: HashSet<H256> = block
+            .transactions
+            .iter()
+            .filter(|hash| state.sends.get(*hash).is_some_and(|t| t.inclusion.is_none()))
+            .copied()
+            .collect();
+        if ours.is_empty() {
+            return;
+        }
+        let ours: Vec<H256> = ours.into_iter().collect();
+        for (hash, result) in self.fetch_receipts(&ours, report).await {
+            let Some(tracked) = state.sends.get_mut(&hash) else {
+                continue;
+            };
+            match result {
+                Ok(Some(receipt)) => {
+                    tracked.inclusion = Some(Inclusion { height: block.number, block_hash: block.hash, receipt });
+                    tracked.lookup = false;
+                    report.included += 1;
+                }
+                // the node serves the block before the receipt
+                Ok(None) => tracked.lookup = true,
+                Err(e) => {
+                    warn!("block tracking on {}: receipt of {:?}: {}", self.network, hash, e);
+                    tracked.lookup = true;
+                    report.errors += 1;
+                }
+            }
+        }
+    }
+
+    /// Applies the receipts of sends with the required confirmations.
+    async fn settle(&self, state: &mut TrackerState, report: &mut BlockTrackingReport) {
+        let Some(cursor) = state.cursor else {
+            return;
+        };
+        let required = self.required_confirmations;
+        let deep_enough = |t: &Tracked| t.inclusion.as_ref().is_some_and(|i| depth(cursor, i.height) >= required);
+        let ready: Vec<H256> = state.sends.iter().filter(|(_, t)| deep_enough(t)).map(|(hash, _)| *hash).collect();
+        for hash in ready {
+            let tracked = &state.sends[&hash];
+            let Some(inclusion) = &tracked.inclusion else {
+                continue;
+            };
+            match apply_receipt(&self.storage, &tracked.record, &inclusion.receipt).await {
+                Ok(success) => {
+                    if success {
+                        report.confirmed += 1;
+                    } else {
+                        report.reverted += 1;
+                    }
+                    state.sends.remove(&hash);
+                }
+                Err(e) => {
+                    warn!("block tracking on {}: {:?}: {}", self.network, hash, e);
+                    report.errors += 1;
+                }
+            }
+        }
+    }
+
+    async fn fetch_receipts(
+        &self,
+        hashes: &[H256],
+        report: &mut BlockTrackingReport,
+    ) -> Vec<(H256, Result<Option<TransactionReceipt>, WalletError>)> {
+        report.receipts_fetched += hashes.len();
+        let lookups = hashes.iter().map(|hash| async move {
+            (*hash, self.chain.transaction_receipt(&self.network, *hash).await)
+        });
+        futures::future::join_all(lookups).await
+    }
+}
+
+#[async_trait]
+impl Job for BlockTracker {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: self.interval(), immediate: true }
+    }
+
+    fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// Fails when nothing could be read or applied and something errored.
+    async fn run(&self, _cancel: CancellationToken) -> anyhow::Result<()> {
+        let report = self.run_once().await;
+        debug!("block tracking cycle on {}: {:?}", self.network, report);
+        if report.errors > 0 && report.blocks + report.confirmed + report.reverted == 0 {
+            anyhow::bail!("block tracking cycle on {} failed ({} errors)", self.network, report.errors);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+//! src/ops/bridge_poller.rs
+//!
+//! Follows up on bridge transfers nobody is watching. `POST /api/bridge`
+//! stores a transfer as `Initiated` and only `GET /api/bridge/:id` reads it
+//! back, so without this job a transfer stays `Initiated` until someone
+//! asks. [`BridgePoller`] asks the serving bridge for the status of every
+//! `Initiated` / `InTransit` transfer and stores the transitions.
+//!
+//! A transfer whose check fails is left alone for a while, doubling the
+//! wait on each further failure, so one broken route does not cost a bridge
+//! call per transfer every cycle.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use parking_lot::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::blockchain::bridge::{bridge_relay, BridgeFactory, BridgeTransaction, BridgeTransactionStatus};
+use crate::core::config::BridgePollerConfig;
+use crate::ops::jobs::{Job, Schedule};
+use crate::ops::leases::LeaderLease;
+use crate::storage::WalletStorage;
+
+/// Scheduler lease job name
+pub const BRIDGE_POLLER_JOB: &str = "bridge_poller";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BridgePollReport {
+    /// Transfers the bridge was asked about
+    pub checked: usize,
+    /// Moved from `Initiated` to `InTransit`
+    pub in_transit: usize,
+    pub completed: usize,
+    pub failed: usize,
+    /// Pending transfers skipped because their check is backing off
+    pub deferred: usize,
+    pub errors: usize,
+}
+
+impl BridgePollReport {
+    fn transitions(&self) -> usize {
+        self.in_transit + self.completed + self.failed
+    }
+}
+
+/// Consecutive failed checks of one transfer and when to try it again
+#[derive(Debug, Clone, Copy)]
+struct Backoff {
+    failures: u32,
+    retry_at: Instant,
+}
+
+enum Outcome {
+    Unchanged,
+    Moved(BridgeTransactionStatus),
+}
+
+pub struct BridgePoller {
+    config: BridgePollerConfig,
+    storage: Arc<WalletStorage>,
+    bridges: Arc<BridgeFactory>,
+    backoff: Mutex<HashMap<String, Backoff>>,
+    lease: Option<LeaderLease>,
+}
+
+impl BridgePoller {
+    pub fn new(config: BridgePollerConfig, storage: Arc<WalletStorage>, bridges: Arc<BridgeFactory>) -> Self {
+        Self { config, storage, bridges, backoff: Mutex::new(HashMap::new()), lease: None }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs.max(1))
+    }
+
+    pub fn with_lease(mut self, lease: LeaderLease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    pub fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// Wait before the next check of a transfer that has failed `failures`
+    /// times in a row.
+    fn backoff_delay(&self, failures: u32) -> Duration {
+        let base = self.config.backoff_base_secs.max(1);
+        let secs = base.saturating_mul(1u64 << failures.saturating_sub(1).min(32));
+        Duration::from_secs(secs.min(self.config.backoff_max_secs.max(base)))
+    }
+
+    /// One pass over pending transfers; stops starting checks once `cancel`
+    /// fires.
+    pub async fn run_once(&self, cancel: &CancellationToken) -> BridgePollReport {
+        let mut report = BridgePollReport::default();
+        let batch = self.config.batch_size.max(1);
+        // transfers backing off are listed too, so they cannot crowd out a full batch
+        let backing_off = self.backoff.lock().len();
+        let pending = match self.storage.list_pending_bridge_transactions(batch + backing_off).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!("bridge poller: failed to list pending transfers: {}", e);
+                report.errors += 1;
+                return report;
+            }
+        };
+
+        let now = Instant::now();
+        let due: Vec<BridgeTransaction> = {
+            let mut backoff = self.backoff.lock();
+            // forget transfers that are no longer pending
+            backoff.retain(|id, _| pending.iter().any(|tx| &tx.id == id));
+            let (due, deferred): (Vec<_>, Vec<_>) =
+                pending.into_iter().partition(|tx| backoff.get(&tx.id).is_none_or(|b| b.retry_at <= now));
+            report.deferred = deferred.len();
+            due.into_iter().take(batch).collect()
+        };
+        report.checked = due.len();
+
+        let mut checks = stream::iter(due)
+            .map(|tx| async move {
+                let outcome = self.check(&tx).await;
+                (tx, outcome)
+            })
+            .buffer_unordered(self.config.max_concurrent_checks.max(1))
+            .take_until(Box::pin(cancel.cancelled()));
+
+        while let Some((tx, outcome)) = checks.next().await {
+            match outcome {
+                Ok(outcome) => {
+                    self.backoff.lock().remove(&tx.id);
+                    match outcome {
+                        Outcome::Unchanged => {}
+                        Outcome::Moved(BridgeTransactionStatus::Completed) => report.completed += 1,
+                        Outcome::Moved(BridgeTransactionStatus::Failed(_)) => report.failed += 1,
+                        Outcome::Moved(_) => report.in_transit += 1,
+                    }
+                }
+                Err(e) => {
+                    let mut backoff = self.backoff.lock();
+                    let failures = backoff.get(&tx.id).map_or(0, |b| b.failures) + 1;
+                    let delay = self.backoff_delay(failures);
+                    backoff.insert(tx.id.clone(), Backoff { failures, retry_at: Instant::now() + delay });
+                    warn!(
+                        "bridge poller: {} ({}->{}) check failed {} time(s), retrying in {}s: {:#}",
+                        tx.id,
+                        tx.from_chain,
+                        tx.to_chain,
+                        failures,
+                        delay.as_secs(),
+                        e
+                    );
+                    report.errors += 1;
+                }
+            }
+        }
+        if report.transitions() > 0 {
+            info!(
+                "bridge poller: {} in transit, {} completed, {} failed",
+                report.in_transit, report.completed, report.failed
+            );
+        }
+        report
+    }
+
+    /// Asks the bridge serving the transfer's route for its status and
+    /// stores it when it moved.
+    async fn check(&self, tx: &BridgeTransaction) -> anyhow::Result<Outcome> {
+        let bridge = self.bridges.for_route(&tx.from_chain, &tx.to_chain)?;
+        // the bridge's own reference when there is one; `POST /api/bridge` stores it as the id
+        let reference = tx.source_tx_hash.as_deref().unwrap_or(&tx.id);
+        let status = bridge_relay(bridge.as_ref(), reference).await?;
+        if status == tx.status {
+            return Ok(Outcome::Unchanged);
+        }
+        // the bridge may lag behind a transfer we already saw move on
+        if status == BridgeTransactionStatus::Initiated {
+            return Ok(Outcome::Unchanged);
+        }
+        self.storage.update_bridge_transaction_status(&tx.id, status.clone(), None).await?;
+        debug!("bridge poller: {} is now {:?}", tx.id, status);
+        Ok(Outcome::Moved(status))
+    }
+}
+
+#[async_trait]
+impl Job for BridgePoller {
+    fn name(&self) -> &str {
+        BRIDGE_POLLER_JOB
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: self.interval(), immediate: true }
+    }
+
+    fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// Fails when the pending transfers could not be listed or every check
+    /// errored; single failing transfers back off on their own.
+    async fn run(&self, cancel: CancellationToken) -> anyhow::Result<()> {
+        let report = self.run_once(&cancel).await;
+        debug!("bridge poll cycle: {:?}", report);
+        if report.errors > 0 && report.errors >= report.checked {
+            anyhow::bail!("bridge poll cycle failed ({} errors)", report.errors);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+//! Chunked AES-256-GCM stream format for backup files.
+//!
+//! ```text
+//! header : MAGIC (6) | nonce prefix (7)
+//! frame  : ciphertext len (u32 BE) | last flag (u8) | ciphertext
+//! nonce  : prefix (7) | chunk counter (u32 BE) | last flag (u8)
+//! ```
+//!
+//! Every frame authenticates the header as AAD, and the counter/last flag in
+//! the nonce make reordered, dropped or truncated frames fail to decrypt.
+//! Only one chunk is held in memory at a time.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, Result};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use zeroize::Zeroizing;
+
+pub const MAGIC: &[u8; 6] = b"IRBK\x00\x01";
+/// Plaintext bytes per frame
+pub const CHUNK_SIZE: usize = 64 * 1024;
+const PREFIX_LEN: usize = 7;
+const HEADER_LEN: usize = MAGIC.len() + PREFIX_LEN;
+const TAG_LEN: usize = 16;
+
+/// Size and digest of an encrypted stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedSummary {
+    pub size_bytes: u64,
+    /// SHA-256 of the ciphertext (hex)
+    pub sha256: String,
+}
+
+fn nonce(prefix: &[u8], counter: u32, last: bool) -> [u8; 12] {
+    let mut n = [0u8; 12];
+    n[..PREFIX_LEN].copy_from_slice(prefix);
+    n[PREFIX_LEN..11].copy_from_slice(&counter.to_be_bytes());
+    n[11] = last as u8;
+    n
+}
+
+/// Fills `buf` from `reader` until it is full or the reader is exhausted.
+async fn read_chunk<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut Vec<u8>) -> Result<()> {
+    buf.clear();
+    buf.resize(CHUNK_SIZE, 0);
+    let mut filled = 0;
+    while filled < CHUNK_SIZE {
+        let n = reader.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    buf.truncate(filled);
+    Ok(())
+}
+
+/// Encrypts `reader` into `writer`.
+pub async fn encrypt_stream<R, W>(key: &[u8; 32], reader: &mut R, writer: &mut W) -> Result<EncryptedSummary>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| anyhow!("invalid backup key"))?;
+    let mut header = [0u8; HEADER_LEN];
+    header[..MAGIC.len()].copy_from_slice(MAGIC);
+    rand::thread_rng().fill_bytes(&mut header[MAGIC.len()..]);
+    let prefix = &header[MAGIC.len()..];
+
+    let mut hasher = Sha256::new();
+    let mut size = HEADER_LEN as u64;
+    writer.write_all(&header).await?;
+    hasher.update(header);
+
+    // one chunk of read-ahead tells us whether the current chunk is the last
+    let mut current = Zeroizing::new(Vec::with_capacity(CHUNK_SIZE));
+    let mut next = Zeroizing::new(Vec::with_capacity(CHUNK_SIZE));
+    read_chunk(reader, &mut current).await?;
+    let mut counter: u32 = 0;
+    loop {
+        if current.len() == CHUNK_SIZE {
+            read_chunk(reader, &mut next).await?;
+        } else {
+            next.clear();
+        }
+        let last = next.is_empty();
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce(prefix, counter, last)),
+                Payload { msg: &current, aad: &header },
+            )
+            .map_err(|_| anyhow!("backup encryption failed"))?;
+
+        let mut frame_header = [0u8; 5];
+        frame_header[..4].copy_from_slice(&(ciphertext.len() as u32).to_be_bytes());
+        frame_header[4] = last as u8;
+        writer.write_all(&frame_header).await?;
+        writer.write_all(&ciphertext).await?;
+        hasher.update(frame_header);
+        hasher.update(&ciphertext);
+        size += (frame_header.len() + ciphertext.len()) as u64;
+
+        if last {
+            break;
+        }
+        std::mem::swap(&mut current, &mut next);
+        counter = counter.checked_add(1).ok_or_else(|| anyhow!("backup too large"))?;
+    }
+    writer.flush().await?;
+
+    Ok(EncryptedSummary { size_bytes: size, sha256: hex::encode(hasher.finalize()) })
+}
+
+/// Decrypts `reader` into `writer`, failing on any tampering or truncation.
+pub async fn decrypt_stream<R, W>(key: &[u8; 32], reader: &mut R, writer: &mut W) -> Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| anyhow!("invalid backup key"))?;
+    let mut header = [0u8; HEADER_LEN];
+    reader.read_exact(&mut header).await.map_err(|_| anyhow!("backup is truncated (header)"))?;
+    if &header[..MAGIC.len()] != MAGIC {
+        bail!("not a backup file (bad magic)");
+    }
+    let prefix = &header[MAGIC.len()..];
+
+    let mut written = 0u64;
+    let mut ciphertext = Vec::with_capacity(CHUNK_SIZE + TAG_LEN);
+    let mut counter: u32 = 0;
+    loop {
+        let mut frame_header = [0u8; 5];
+        reader.read_exact(&mut frame_header).await.map_err(|_| anyhow!("backup is truncated"))?;
+        let len = u32::from_be_bytes([frame_header[0], frame_header[1], frame_header[2], frame_header[3]]) as usize;
+        let last = match frame_header[4] {
+            0 => false,
+            1 => true,
+            _ => bail!("backup frame {} is corrupt", counter),
+        };
+        if !(TAG_LEN..=CHUNK_SIZE + TAG_LEN).contains(&len) {
+            bail!("backup frame {} has invalid length {}", counter, len);
+        }
+        ciphertext.resize(len, 0);
+        reader.read_exact(&mut ciphertext).await.map_err(|_| anyhow!("backup is truncated"))?;
+
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let plaintext = Zeroizing::new(
+            cipher
+                .decrypt(
+                    Nonce::from_slice(&nonce(prefix, counter, last)),
+                    Payload { msg: &ciphertext, aad: &header },
+                )
+                .map_err(|_| anyhow!("backup frame {} failed authentication", counter))?,
+        );
+        writer.write_all(&plaintext).await?;
+        written += plaintext.len() as u64;
+
+        if last {
+            break;
+        }
+        counter = counter.checked_add(1).ok_or_else(|| anyhow!("backup too large"))?;
+    }
+    let mut trailing = [0u8; 1];
+    if reader.read(&mut trailing).await? != 0 {
+        bail!("unexpected data after final backup frame");
+    }
+    writer.flush().await?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [7u8; 32];
+
+    async fn round_trip(len: usize) -> (Vec<u8>, Vec<u8>) {
+        let plain: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+        let mut encrypted = Vec::new();
+        let summary = encrypt_stream(&KEY, &mut plain.as_slice(), &mut encrypted).await.unwrap();
+        assert_eq!(summary.size_bytes, encrypted.len() as u64);
+        let mut decrypted = Vec::new();
+        decrypt_stream(&KEY, &mut encrypted.as_slice(), &mut decrypted).await.unwrap();
+        assert_eq!(decrypted, plain);
+        (plain, encrypted)
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_chunk_boundaries() {
+        for len in [0, 1, CHUNK_SIZE - 1, CHUNK_SIZE, CHUNK_SIZE + 1, 3 * CHUNK_SIZE] {
+            round_trip(len).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_truncation_and_tampering_detected() {
+        let (_, encrypted) = round_trip(2 * CHUNK_SIZE).await;
+        let frame = 5 + CHUNK_SIZE + TAG_LEN;
+
+        // drop the final frame: the stream never reaches a last frame
+        assert_eq!(encrypted.len(), HEADER_LEN + 2 * frame);
+        let truncated = &encrypted[..HEADER_LEN + frame];
+        assert!(decrypt_stream(&KEY, &mut &truncated[..], &mut Vec::new()).await.is_err());
+
+        // claim an intermediate frame is the last one
+        let mut forged = encrypted[..HEADER_LEN + frame].to_vec();
+        forged[HEADER_LEN + 4] = 1;
+        assert!(decrypt_stream(&KEY, &mut forged.as_slice(), &mut Vec::new()).await.is_err());
+
+        let mut flipped = encrypted.clone();
+        flipped[HEADER_LEN + 100] ^= 0x01;
+        assert!(decrypt_stream(&KEY, &mut flipped.as_slice(), &mut Vec::new()).await.is_err());
+
+        let wrong_key = [8u8; 32];
+        assert!(decrypt_stream(&wrong_key, &mut encrypted.as_slice(), &mut Vec::new()).await.is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! src/ops/db_backup/mod.rs
+//!
+//! Scheduled, encrypted database backups.
+//!
+//! Each run takes a consistent snapshot with `VACUUM INTO`, encrypts it with
+//! the master key (`WALLET_ENC_KEY`) in fixed-size AES-GCM chunks, uploads it
+//! to the configured sink and then proves the upload restorable: the object is
+//! downloaded again, decrypted, opened read-only and `PRAGMA integrity_check`ed.
+//! Every attempt lands in `backup_history`; pruning then applies the
+//! keep-last / keep-daily policy to verified backups and deletes failed ones.
+
+pub mod crypto;
+pub mod retention;
+pub mod sink;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+use zeroize::Zeroizing;
+
+use crate::core::config::BackupConfig;
+use crate::monitoring::WalletMetrics;
+use crate::ops::jobs::{Job, Schedule};
+use crate::ops::leases::LeaderLease;
+use crate::ops::maintenance::MaintenanceMode;
+use crate::storage::{NewBackupRecord, WalletStorage, BACKUP_FAILED, BACKUP_VERIFIED};
+
+pub use sink::{check_key, BackupSink, FilesystemSink, S3Sink};
+
+/// Outcome of one backup run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BackupRunReport {
+    /// `backup_history` row written for this run
+    pub id: Option<i64>,
+    pub object_key: String,
+    /// `verified` or `failed`; empty when the run was skipped
+    pub status: String,
+    pub size_bytes: u64,
+    pub sha256: Option<String>,
+    pub error: Option<String>,
+    /// Older backups deleted by retention
+    pub pruned: usize,
+    /// The run was skipped (maintenance mode)
+    pub skipped: bool,
+}
+
+/// Outcome of a manual [`BackupScheduler::try_vacuum_into`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct VacuumIntoReport {
+    pub object_key: String,
+    pub size_bytes: u64,
+    pub sha256: Option<String>,
+    /// Downloaded again and integrity-checked
+    pub verified: bool,
+    pub error: Option<String>,
+}
+
+/// Returned by [`BackupScheduler::try_run`] when a run is already in progress.
+#[derive(Debug, thiserror::Error)]
+#[error("a backup is already running")]
+pub struct BackupInProgress;
+
+/// Scratch directory removed when dropped, even on error paths.
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn create() -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("wallet-backup-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).context("failed to create backup scratch directory")?;
+        Ok(Self(path))
+    }
+
+    fn join(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        // snapshots are plaintext: never leave them behind
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+pub struct BackupScheduler {
+    config: BackupConfig,
+    storage: Arc<WalletStorage>,
+    sink: Arc<dyn BackupSink>,
+    maintenance: Arc<MaintenanceMode>,
+    metrics: Arc<WalletMetrics>,
+    /// Explicit key; `WALLET_ENC_KEY` is read at run time otherwise
+    key: Option<Zeroizing<[u8; 32]>>,
+    running: Mutex<()>,
+    /// Scheduled runs only happen on the instance holding this lease
+    lease: Option<LeaderLease>,
+}
+
+/// Scheduler lease job name
+pub const BACKUPS_JOB: &str = "db_backups";
+
+impl BackupScheduler {
+    pub fn new(
+        config: BackupConfig,
+        storage: Arc<WalletStorage>,
+        sink: Arc<dyn BackupSink>,
+        maintenance: Arc<MaintenanceMode>,
+        metrics: Arc<WalletMetrics>,
+    ) -> Self {
+        Self { config, storage, sink, maintenance, metrics, key: None, running: Mutex::new(()), lease: None }
+    }
+
+    pub fn with_lease(mut self, lease: LeaderLease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    pub fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs.max(60))
+    }
+
+    /// Uses `key` instead of the master key from the environment.
+    pub fn with_key(mut self, key: [u8; 32]) -> Self {
+        self.key = Some(Zeroizing::new(key));
+        self
+    }
+
+    pub fn config(&self) -> &BackupConfig {
+        &self.config
+    }
+
+    fn key(&self) -> Result<Zeroizing<[u8; 32]>> {
+        match &self.key {
+            Some(k) => Ok(k.clone()),
+            None => crate::core::key_management::load_master_enc_key().map(Zeroizing::new),
+        }
+    }
+
+    /// Like [`run_once`](Self::run_once), but fails fast instead of queueing
+    /// behind a run that is already in progress.
+    pub async fn try_run(&self) -> Result<BackupRunReport, BackupInProgress> {
+        let guard = self.running.try_lock().map_err(|_| BackupInProgress)?;
+        let report = self.run_locked().await;
+        drop(guard);
+        Ok(report)
+    }
+
+    /// Writes an encrypted, verified snapshot to `object_key` in the backup
+    /// sink outside the schedule: no history row, never pruned. Shares the
+    /// lock with scheduled runs.
+    pub async fn try_vacuum_into(&self, object_key: &str) -> Result<VacuumIntoReport, BackupInProgress> {
+        let _guard = self.running.try_lock().map_err(|_| BackupInProgress)?;
+        let mut report = VacuumIntoReport { object_key: object_key.to_string(), ..Default::default() };
+        let result = match self.write_backup(object_key).await {
+            Ok(summary) => {
+                report.size_bytes = summary.size_bytes;
+                report.sha256 = Some(summary.sha256);
+                self.verify(object_key).await
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => {
+                report.verified = true;
+                info!("database snapshot written to {} ({} bytes)", object_key, report.size_bytes);
+            }
+            Err(e) => {
+                error!("database snapshot to {} failed: {:#}", object_key, e);
+                self.metrics.record_backup_failure();
+                report.error = Some(format!("{:#}", e));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Takes, verifies and records one backup, then prunes old ones.
+    pub async fn run_once(&self) -> BackupRunReport {
+        let _guard = self.running.lock().await;
+        self.run_locked().await
+    }
+
+    async fn run_locked(&self) -> BackupRunReport {
+        let mut report = BackupRunReport::default();
+        if self.maintenance.is_enabled() {
+            debug!("database backup skipped: maintenance mode");
+            report.skipped = true;
+            return report;
+        }
+
+        let now = Utc::now();
+        report.object_key = format!(
+            "wallet-{}-{}.db.enc",
+            now.format("%Y%m%dT%H%M%SZ"),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+
+        let result = self.write_backup(&report.object_key).await;
+        let verified = match result {
+            Ok(summary) => {
+                report.size_bytes = summary.size_bytes;
+                report.sha256 = Some(summary.sha256);
+                self.verify(&report.object_key).await
+            }
+            Err(e) => Err(e),
+        };
+        match verified {
+            Ok(()) => {
+                report.status = BACKUP_VERIFIED.to_string();
+                info!("database backup {} verified ({} bytes)", report.object_key, report.size_bytes);
+            }
+            Err(e) => {
+                error!("database backup {} failed: {:#}", report.object_key, e);
+                self.metrics.record_backup_failure();
+                report.status = BACKUP_FAILED.to_string();
+                report.error = Some(format!("{:#}", e));
+            }
+        }
+
+        let record = NewBackupRecord {
+            object_key: &report.object_key,
+            sink: self.sink.kind(),
+            status: &report.status,
+            size_bytes: report.size_bytes as i64,
+            sha256: report.sha256.as_deref(),
+            error: report.error.as_deref(),
+            created_at: now.timestamp(),
+        };
+        match self.storage.record_backup(&record).await {
+            Ok(id) => report.id = Some(id),
+            Err(e) => {
+                // without a history row retention would never clean this object up
+                error!("database backup {} could not be recorded: {}", report.object_key, e);
+                self.metrics.record_backup_failure();
+                return report;
+            }
+        }
+
+        match self.prune().await {
+            Ok(n) => report.pruned = n,
+            Err(e) => {
+                warn!("database backup pruning failed: {:#}", e);
+                self.metrics.record_backup_failure();
+            }
+        }
+        report
+    }
+
+    async fn write_backup(&self, object_key: &str) -> Result<crypto::EncryptedSummary> {
+        let key = self.key()?;
+        let scratch = ScratchDir::create()?;
+        let snapshot = scratch.join("snapshot.db");
+        let encrypted = scratch.join("snapshot.db.enc");
+
+        self.storage.snapshot_into(&snapshot).await?;
+        let summary = {
+            let mut input = tokio::fs::File::open(&snapshot).await?;
+            let mut output = tokio::io::BufWriter::new(tokio::fs::File::create(&encrypted).await?);
+            crypto::encrypt_stream(&key, &mut input, &mut output).await?
+        };
+        // drop the plaintext as soon as it is no longer needed
+        tokio::fs::remove_file(&snapshot).await?;
+
+        self.sink.put(object_key, &encrypted).await?;
+        Ok(summary)
+    }
+
+    /// Downloads `object_key`, decrypts it and runs `PRAGMA integrity_check`
+    /// on the result.
+    pub async fn verify(&self, object_key: &str) -> Result<()> {
+        let key = self.key()?;
+        let scratch = ScratchDir::create()?;
+        let encrypted = scratch.join("verify.db.enc");
+        let restored = scratch.join("verify.db");
+
+        self.sink.get(object_key, &encrypted).await?;
+        {
+            let mut input = tokio::io::BufReader::new(tokio::fs::File::open(&encrypted).await?);
+            let mut output = tokio::fs::File::create(&restored).await?;
+            crypto::decrypt_stream(&key, &mut input, &mut output).await?;
+        }
+        check_integrity(&restored).await
+    }
+
+    /// Applies the retention policy; returns how many backups were deleted.
+    pub async fn prune(&self) -> Result<usize> {
+        let backups = self.storage.unpruned_backups().await?;
+        let verified: Vec<(i64, i64)> =
+            backups.iter().filter(|b| b.status == BACKUP_VERIFIED).map(|b| (b.id, b.created_at)).collect();
+        let keep = retention::select_retained(&verified, self.config.keep_last, self.config.keep_daily);
+
+        let mut pruned = 0;
+        for backup in backups.iter().filter(|b| !keep.contains(&b.id)) {
+            self.sink
+                .delete(&backup.object_key)
+                .await
+                .with_context(|| format!("failed to delete backup {}", backup.object_key))?;
+            self.storage.mark_backup_pruned(backup.id, Utc::now()).await?;
+            pruned += 1;
+        }
+        if pruned > 0 {
+            info!("database backups: pruned {}", pruned);
+        }
+        Ok(pruned)
+    }
+}
+
+#[async_trait]
+impl Job for BackupScheduler {
+    fn name(&self) -> &str {
+        BACKUPS_JOB
+    }
+
+    /// Not at startup: a restart loop must not turn into a backup loop.
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: self.interval(), immediate: false }
+    }
+
+    fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    async fn run(&self, _cancel: CancellationToken) -> Result<()> {
+        let report = self.run_once().await;
+        debug!("database backup run: {:?}", report);
+        match report.error {
+            Some(e) if report.status == BACKUP_FAILED => bail!("backup {} failed: {}", report.object_key, e),
+            _ => Ok(()),
+        }
+    }
+}
+
+async fn check_integrity(path: &Path) -> Result<()> {
+    let mut conn = SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .connect()
+        .await
+        .context("restored backup cannot be opened")?;
+    let rows: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(&mut conn)
+        .await
+        .context("integrity check could not run")?;
+    conn.close().await.ok();
+    if rows.len() != 1 || rows[0] != "ok" {
+        bail!("integrity check failed: {}", rows.join("; "));
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! Which backups survive pruning.
+//!
+//! Policy: the `keep_last` newest backups, plus the newest backup of each of
+//! the `keep_daily` most recent UTC days that have one. The two sets overlap,
+//! so a quiet day costs nothing extra.
+
+use std::collections::{BTreeSet, HashSet};
+
+use chrono::{DateTime, NaiveDate};
+
+/// Ids from `backups` (`(id, created_at)` in Unix seconds) to keep.
+pub fn select_retained(backups: &[(i64, i64)], keep_last: usize, keep_daily: usize) -> BTreeSet<i64> {
+    let mut sorted = backups.to_vec();
+    // newest first; id breaks ties between backups taken in the same second
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+
+    let mut keep: BTreeSet<i64> = sorted.iter().take(keep_last).map(|(id, _)| *id).collect();
+
+    let mut days: HashSet<NaiveDate> = HashSet::new();
+    for (id, created_at) in &sorted {
+        if days.len() >= keep_daily {
+            break;
+        }
+        let Some(day) = DateTime::from_timestamp(*created_at, 0).map(|t| t.date_naive()) else { continue };
+        if days.insert(day) {
+            keep.insert(*id);
+        }
+    }
+    keep
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn test_keep_last_only() {
+        let backups: Vec<_> = (1..=5).map(|i| (i, i * 60)).collect();
+        assert_eq!(select_retained(&backups, 2, 0), BTreeSet::from([4, 5]));
+        assert!(select_retained(&backups, 0, 0).is_empty());
+        assert_eq!(select_retained(&backups, 10, 0).len(), 5);
+    }
+
+    #[test]
+    fn test_daily_keeps_newest_of_each_day() {
+        // three backups per day over four days
+        let backups: Vec<_> = (0..12).map(|i| (i + 1, (i / 3) * DAY + (i % 3) * 3600)).collect();
+        // newest of days 3, 2 (ids 12, 9) plus keep_last picks 12 again
+        assert_eq!(select_retained(&backups, 1, 2), BTreeSet::from([9, 12]));
+        assert_eq!(select_retained(&backups, 2, 4), BTreeSet::from([3, 6, 9, 11, 12]));
+    }
+
+    #[test]
+    fn test_daily_skips_days_without_backups() {
+        let backups = vec![(1, 0), (2, 5 * DAY), (3, 9 * DAY)];
+        assert_eq!(select_retained(&backups, 0, 2), BTreeSet::from([2, 3]));
+    }
+
+    #[test]
+    fn test_input_order_does_not_matter() {
+        let backups = vec![(3, 300), (1, 100), (2, 200)];
+        assert_eq!(select_retained(&backups, 1, 0), BTreeSet::from([3]));
+    }
+}
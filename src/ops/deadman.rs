@@ -0,0 +1,333 @@
+//! src/ops/deadman.rs
+//!
+//! Background evaluator for dead-man's switch policies.
+//!
+//! Each cycle walks the active policies: an armed policy past a point of its
+//! warning schedule gets a `deadman.warning` journal event, and one past its
+//! deadline is flipped to triggered (Critical security event plus audit
+//! entry) and swept. The sweep opens the policy's sealed capability for the
+//! stored beneficiary and sends balance minus one transfer fee on each
+//! network; networks that fail stay pending and are retried on later cycles.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ethers::utils::format_ether;
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use crate::blockchain::gas_oracle::{min_transfer_cost, GasOracle, STANDARD_TRANSFER_GAS};
+use crate::core::config::DeadmanConfig;
+use crate::core::errors::WalletError;
+use crate::core::wallet_manager::WalletManager;
+use crate::feature_flags::{FeatureFlags, Flag};
+use crate::intents::SigningIntentLog;
+use crate::monitoring::{SecurityEvent, SecurityEventType, SecuritySeverity};
+use crate::ops::jobs::{Job, Schedule};
+use crate::ops::leases::LeaderLease;
+use crate::ops::maintenance::MaintenanceMode;
+use crate::security::error_sanitizer::sanitize_error_message;
+use crate::security::key_usage::KeyUsageTracker;
+use crate::storage::{DeadmanPolicy, WalletStorage, DEADMAN_ARMED};
+
+/// Scheduler lease job name
+pub const DEADMAN_JOB: &str = "deadman_switch";
+
+/// Result of sweeping one network of a triggered policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeadmanSweep {
+    pub wallet_name: String,
+    pub network: String,
+    /// `sent`, `empty` (balance does not cover the fee) or `failed`
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of one evaluation cycle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeadmanCycleReport {
+    /// Warning events emitted
+    pub warnings: usize,
+    /// Policies that fired this cycle
+    pub triggered: usize,
+    pub sweeps: Vec<DeadmanSweep>,
+    /// Policy reads or transitions that failed
+    pub failed: usize,
+    /// The whole cycle was skipped (maintenance mode)
+    pub skipped: bool,
+    /// Policies left alone because `deadman_switch` is off for their wallet
+    pub disabled: usize,
+}
+
+pub struct DeadmanEvaluator {
+    config: DeadmanConfig,
+    storage: Arc<WalletStorage>,
+    wallet_manager: Arc<WalletManager>,
+    gas_oracle: Arc<dyn GasOracle>,
+    signing_intents: Arc<SigningIntentLog>,
+    key_usage: Arc<KeyUsageTracker>,
+    maintenance: Arc<MaintenanceMode>,
+    lease: Option<LeaderLease>,
+    /// `None` evaluates every policy
+    flags: Option<Arc<FeatureFlags>>,
+}
+
+impl DeadmanEvaluator {
+    pub fn new(
+        config: DeadmanConfig,
+        storage: Arc<WalletStorage>,
+        wallet_manager: Arc<WalletManager>,
+        gas_oracle: Arc<dyn GasOracle>,
+        signing_intents: Arc<SigningIntentLog>,
+        key_usage: Arc<KeyUsageTracker>,
+        maintenance: Arc<MaintenanceMode>,
+    ) -> Self {
+        Self {
+            config,
+            storage,
+            wallet_manager,
+            gas_oracle,
+            signing_intents,
+            key_usage,
+            maintenance,
+            lease: None,
+            flags: None,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs.max(1))
+    }
+
+    /// Only tick on the instance holding `lease`.
+    pub fn with_lease(mut self, lease: LeaderLease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    pub fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// Skip the policies of wallets with [`Flag::DeadmanSwitch`] off.
+    pub fn with_flags(mut self, flags: Arc<FeatureFlags>) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    /// One pass over all active policies.
+    pub async fn run_once(&self) -> DeadmanCycleReport {
+        let mut report = DeadmanCycleReport::default();
+        if self.maintenance.is_enabled() {
+            debug!("dead-man evaluation skipped: maintenance mode");
+            report.skipped = true;
+            return report;
+        }
+        let policies = match self.storage.active_deadman_policies().await {
+            Ok(policies) => policies,
+            Err(e) => {
+                warn!("dead-man evaluation: failed to list policies: {}", e);
+                report.failed += 1;
+                return report;
+            }
+        };
+
+        let now = self.storage.clock().now().timestamp();
+        for policy in policies {
+            if let Some(flags) = &self.flags {
+                if !flags.is_enabled(Flag::DeadmanSwitch, Some(&policy.wallet_name)).await {
+                    debug!("dead-man switch of {} not evaluated: deadman_switch is off", policy.wallet_name);
+                    report.disabled += 1;
+                    continue;
+                }
+            }
+            if policy.status != DEADMAN_ARMED {
+                report.sweeps.extend(self.sweep(&policy, &policy.pending_networks).await);
+                continue;
+            }
+            if now >= policy.deadline() {
+                match self.storage.trigger_deadman(&policy).await {
+                    Ok(true) => {
+                        report.triggered += 1;
+                        self.report_trigger(&policy).await;
+                        report.sweeps.extend(self.sweep(&policy, &policy.networks).await);
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        error!("dead-man switch of {} could not be triggered: {}", policy.wallet_name, e);
+                        report.failed += 1;
+                    }
+                }
+                continue;
+            }
+            let due = policy.warnings_due(now);
+            if due > policy.warnings_sent {
+                match self.storage.record_deadman_warning(&policy, due).await {
+                    Ok(true) => {
+                        info!(
+                            "dead-man switch of {} fires in {}s without a check-in",
+                            policy.wallet_name,
+                            policy.deadline() - now
+                        );
+                        report.warnings += 1;
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        warn!("dead-man warning for {} failed: {}", policy.wallet_name, e);
+                        report.failed += 1;
+                    }
+                }
+            }
+        }
+        report
+    }
+
+    async fn report_trigger(&self, policy: &DeadmanPolicy) {
+        self.key_usage
+            .security_monitor()
+            .report_security_event(SecurityEvent {
+                event_type: SecurityEventType::DeadmanSwitchTriggered,
+                description: format!(
+                    "dead-man switch of wallet {} fired after {}s without a check-in; sweeping to {}",
+                    policy.wallet_name, policy.inactivity_secs, policy.beneficiary
+                ),
+                severity: SecuritySeverity::Critical,
+                timestamp: self.storage.clock().now(),
+                source_ip: None,
+                wallet_id: Some(policy.wallet_name.clone()),
+            })
+            .await;
+        let details = serde_json::json!({
+            "beneficiary": policy.beneficiary,
+            "networks": policy.networks,
+            "last_checkin_at": policy.last_checkin_at,
+            "deadline": policy.deadline(),
+        });
+        if let Err(e) =
+            self.storage.log_action(&policy.wallet_name, "deadman.triggered", &details.to_string(), None, None).await
+        {
+            error!("failed to audit dead-man trigger of {}: {}", policy.wallet_name, e);
+        }
+    }
+
+    /// Sweeps `networks` of a triggered policy and records what is left.
+    async fn sweep(&self, policy: &DeadmanPolicy, networks: &[String]) -> Vec<DeadmanSweep> {
+        // 只能为存储的受益人打开；受益人列被篡改时解密失败
+        let signer = match policy.capability.open(&policy.beneficiary, &policy.networks) {
+            Ok(signer) => signer,
+            Err(e) => {
+                error!("dead-man capability of {} cannot be opened: {}", policy.wallet_name, e);
+                return networks
+                    .iter()
+                    .map(|network| DeadmanSweep {
+                        wallet_name: policy.wallet_name.clone(),
+                        network: network.clone(),
+                        status: "failed".to_string(),
+                        amount: None,
+                        tx_hash: None,
+                        error: Some("Sweep capability unavailable".to_string()),
+                    })
+                    .collect();
+            }
+        };
+        let address = format!("{:#x}", signer.address());
+
+        let mut results = Vec::new();
+        for network in networks {
+            let mut result = DeadmanSweep {
+                wallet_name: policy.wallet_name.clone(),
+                network: network.clone(),
+                status: "failed".to_string(),
+                amount: None,
+                tx_hash: None,
+                error: None,
+            };
+            let outcome = async {
+                self.wallet_manager.ensure_network_allowed(&policy.wallet_name, network)?;
+                let gas_price = self.gas_oracle.gas_price(network).await?;
+                let balance = self.gas_oracle.native_balance(network, &address).await?;
+                let Some(amount) = balance.checked_sub(min_transfer_cost(gas_price)).filter(|a| !a.is_zero()) else {
+                    return Ok(None);
+                };
+                self.key_usage.record_signature(&policy.wallet_name).await?;
+                // 固定 gas 与价格：节点另填更高的价格会让余额不够付手续费
+                let mut tx = signer.transfer(amount);
+                tx.set_gas(STANDARD_TRANSFER_GAS);
+                tx.set_gas_price(gas_price);
+                let tx_hash = self
+                    .signing_intents
+                    .send(&policy.wallet_name, network, signer.signer(), tx)
+                    .await
+                    .map_err(|e| WalletError::TransactionFailed(e.to_string()))?;
+                Ok::<_, WalletError>(Some((amount, tx_hash)))
+            }
+            .await;
+            match outcome {
+                Ok(Some((amount, tx_hash))) => {
+                    result.status = "sent".to_string();
+                    result.amount = Some(format_ether(amount));
+                    result.tx_hash = Some(tx_hash);
+                }
+                Ok(None) => result.status = "empty".to_string(),
+                Err(e) => {
+                    warn!("dead-man sweep of {} on {} failed: {}", policy.wallet_name, network, e);
+                    result.error = Some(sanitize_error_message(&e.to_string()));
+                }
+            }
+            let details = serde_json::json!({
+                "network": network,
+                "to": policy.beneficiary,
+                "status": result.status,
+                "amount": result.amount,
+                "tx_hash": result.tx_hash,
+                "error": result.error,
+            });
+            if let Err(e) =
+                self.storage.log_action(&policy.wallet_name, "deadman.sweep", &details.to_string(), None, None).await
+            {
+                error!("failed to audit dead-man sweep of {}: {}", policy.wallet_name, e);
+            }
+            results.push(result);
+        }
+
+        let pending: Vec<String> =
+            results.iter().filter(|r| r.status == "failed").map(|r| r.network.clone()).collect();
+        if let Err(e) = self.storage.set_deadman_pending(&policy.wallet_name, &pending).await {
+            error!("failed to record dead-man sweep progress of {}: {}", policy.wallet_name, e);
+        }
+        results
+    }
+}
+
+#[async_trait]
+impl Job for DeadmanEvaluator {
+    fn name(&self) -> &str {
+        DEADMAN_JOB
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: self.interval(), immediate: true }
+    }
+
+    fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// Failed sweeps are retried by the next cycle on their own; the run
+    /// fails when policies could not be read or transitioned.
+    async fn run(&self, _cancel: CancellationToken) -> anyhow::Result<()> {
+        let report = self.run_once().await;
+        debug!("dead-man evaluation cycle: {:?}", report);
+        if report.failed > 0 {
+            anyhow::bail!("{} dead-man policies could not be evaluated", report.failed);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,360 @@
+//! Envelope KEK rotation and classical ↔ quantum envelope migration.
+//!
+//! Wallets created through [`crate::core::wallet::create`] store their master
+//! key sealed under a key derived (HKDF with a per-wallet salt and
+//! `hkdf_info_v2`) from the process KEK in `WALLET_ENC_KEY`. Rotation unseals
+//! every wallet with the old KEK, reseals it under the new one with a fresh
+//! salt and nonce, optionally switching the envelope mode, and checks that
+//! the new envelope opens again before it is written.
+//!
+//! A dry run executes the same updates inside a storage transaction that is
+//! always rolled back and returns the same [`RotationReport`] a real run
+//! does; only `dry_run` and `applied` differ, so the two can be diffed. A real
+//! run commits only when every wallet passed: a KEK swap must not leave some
+//! wallets sealed under the retired key.
+//!
+//! Wallets whose key is sealed with a password (the wallet manager's PBKDF2
+//! format) are not sealed under the KEK and fail the decrypt probe.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm,
+};
+use base64::Engine;
+use hkdf::Hkdf;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::core::errors::WalletError;
+use crate::core::wallet_info::SecureWalletData;
+use crate::crypto::quantum::QuantumSafeEncryption;
+use crate::storage::{RewrappedEnvelope, WalletEnvelope, WalletStorage};
+
+const GCM_NONCE_LEN: usize = 12;
+
+/// How a wallet's master key is sealed under the KEK
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvelopeMode {
+    /// AES-256-GCM, AAD = HKDF info
+    Classical,
+    /// [`QuantumSafeEncryption`] envelope
+    Quantum,
+}
+
+impl EnvelopeMode {
+    fn of(quantum_safe: bool) -> Self {
+        if quantum_safe {
+            EnvelopeMode::Quantum
+        } else {
+            EnvelopeMode::Classical
+        }
+    }
+}
+
+impl std::str::FromStr for EnvelopeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classical" => Ok(EnvelopeMode::Classical),
+            "quantum" => Ok(EnvelopeMode::Quantum),
+            other => Err(format!("unknown envelope mode '{}' (expected classical or quantum)", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeStatus {
+    Passed,
+    Failed,
+    /// Not attempted because an earlier step failed
+    Skipped,
+}
+
+/// Outcome for one wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletRotation {
+    pub wallet: String,
+    pub from_mode: EnvelopeMode,
+    pub to_mode: EnvelopeMode,
+    /// Unsealing with the old KEK
+    pub decrypt_probe: ProbeStatus,
+    /// Resealing under the new KEK and opening the result again
+    pub encrypt_probe: ProbeStatus,
+    /// Pending work that blocks a mode change
+    pub blockers: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl WalletRotation {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationTotals {
+    pub wallets: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub decrypt_failed: usize,
+    pub blocked: usize,
+    pub mode_changes: usize,
+}
+
+/// Result of a rotation; the same shape for dry and real runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationReport {
+    pub dry_run: bool,
+    /// Whether the new envelopes were committed
+    pub applied: bool,
+    /// Recorded on every resealed wallet and in the `key.rotated` journal event
+    pub kek_id: String,
+    pub target_mode: Option<EnvelopeMode>,
+    pub totals: RotationTotals,
+    pub wallets: Vec<WalletRotation>,
+}
+
+/// Decodes a base64 KEK as accepted in `WALLET_ENC_KEY`; all zeros is refused.
+pub fn decode_kek(b64: &str) -> Result<Zeroizing<[u8; 32]>, WalletError> {
+    let raw = Zeroizing::new(
+        base64::engine::general_purpose::STANDARD
+            .decode(b64.trim())
+            .map_err(|_| WalletError::CryptoError("KEK must be base64(32)".into()))?,
+    );
+    if raw.len() != 32 {
+        return Err(WalletError::CryptoError("KEK must be 32 bytes".into()));
+    }
+    if raw.iter().all(|&b| b == 0) {
+        return Err(WalletError::CryptoError("Insecure KEK (all zeros)".into()));
+    }
+    let mut kek = Zeroizing::new([0u8; 32]);
+    kek.copy_from_slice(&raw);
+    Ok(kek)
+}
+
+/// Mirrors the wallet creation guard: the quantum envelope is simulated and
+/// only available to test builds.
+fn quantum_allowed() -> bool {
+    cfg!(any(test, feature = "test-env"))
+        || std::env::var("RUST_TEST_THREADS").is_ok()
+        || std::env::var("WALLET_TEST_CONSTRUCTOR").is_ok()
+}
+
+pub struct EnvelopeRotation {
+    old_kek: Zeroizing<[u8; 32]>,
+    new_kek: Zeroizing<[u8; 32]>,
+    kek_id: String,
+    target: Option<EnvelopeMode>,
+}
+
+impl EnvelopeRotation {
+    /// The KEK id defaults to a fingerprint of the new KEK.
+    pub fn new(old_kek: Zeroizing<[u8; 32]>, new_kek: Zeroizing<[u8; 32]>) -> Self {
+        let digest = Sha256::digest(new_kek.as_slice());
+        let kek_id = format!("kek-{}", hex::encode(&digest[..8]));
+        Self { old_kek, new_kek, kek_id, target: None }
+    }
+
+    pub fn with_kek_id(mut self, kek_id: impl Into<String>) -> Self {
+        self.kek_id = kek_id.into();
+        self
+    }
+
+    /// Also migrates every wallet to `mode`. Wallets that change mode while
+    /// they have pending approvals or transactions are reported as blocked.
+    pub fn with_target(mut self, mode: EnvelopeMode) -> Self {
+        self.target = Some(mode);
+        self
+    }
+
+    pub async fn run(&self, storage: &WalletStorage, dry_run: bool) -> Result<RotationReport, WalletError> {
+        if self.target == Some(EnvelopeMode::Quantum) && !quantum_allowed() {
+            return Err(WalletError::ValidationError(
+                "quantum_safe mode is not supported in production builds".into(),
+            ));
+        }
+        let quantum = QuantumSafeEncryption::new().map_err(|e| WalletError::CryptoError(e.to_string()))?;
+
+        let mut wallets = Vec::new();
+        let summary = storage
+            .rewrap_wallet_envelopes(!dry_run, &self.kek_id, |envelope| {
+                let (outcome, rewrapped) = self.rewrap(&quantum, envelope);
+                wallets.push(outcome);
+                rewrapped
+            })
+            .await
+            .map_err(|e| WalletError::StorageError(e.to_string()))?;
+
+        let totals = RotationTotals {
+            wallets: wallets.len(),
+            succeeded: wallets.iter().filter(|w| w.succeeded()).count(),
+            failed: wallets.iter().filter(|w| !w.succeeded()).count(),
+            decrypt_failed: wallets.iter().filter(|w| w.decrypt_probe == ProbeStatus::Failed).count(),
+            blocked: wallets.iter().filter(|w| !w.blockers.is_empty()).count(),
+            mode_changes: wallets.iter().filter(|w| w.from_mode != w.to_mode).count(),
+        };
+        Ok(RotationReport {
+            dry_run,
+            applied: summary.committed,
+            kek_id: self.kek_id.clone(),
+            target_mode: self.target,
+            totals,
+            wallets,
+        })
+    }
+
+    fn rewrap(
+        &self,
+        quantum: &QuantumSafeEncryption,
+        envelope: &WalletEnvelope,
+    ) -> (WalletRotation, Option<RewrappedEnvelope>) {
+        let from_mode = EnvelopeMode::of(envelope.quantum_safe);
+        let to_mode = self.target.unwrap_or(from_mode);
+        let mut outcome = WalletRotation {
+            wallet: envelope.name.clone(),
+            from_mode,
+            to_mode,
+            decrypt_probe: ProbeStatus::Skipped,
+            encrypt_probe: ProbeStatus::Skipped,
+            blockers: Vec::new(),
+            error: None,
+        };
+        if from_mode != to_mode {
+            if envelope.pending_approvals > 0 {
+                outcome.blockers.push(format!("{} pending approvals", envelope.pending_approvals));
+            }
+            if envelope.pending_transactions > 0 {
+                outcome.blockers.push(format!("{} pending transactions", envelope.pending_transactions));
+            }
+        }
+
+        let mut data: SecureWalletData = match bincode::deserialize(&envelope.encrypted_data) {
+            Ok(data) => data,
+            Err(e) => {
+                outcome.decrypt_probe = ProbeStatus::Failed;
+                outcome.error = Some(format!("Unreadable wallet record: {}", e));
+                return (outcome, None);
+            }
+        };
+        let master_key = match unseal(quantum, &data, &self.old_kek, from_mode) {
+            Ok(key) => key,
+            Err(e) => {
+                outcome.decrypt_probe = ProbeStatus::Failed;
+                outcome.error = Some(e.to_string());
+                return (outcome, None);
+            }
+        };
+        outcome.decrypt_probe = ProbeStatus::Passed;
+
+        let sealed = seal(quantum, &mut data, &master_key, &self.new_kek, to_mode).and_then(|()| {
+            data.kek_id = Some(self.kek_id.clone());
+            let reopened = unseal(quantum, &data, &self.new_kek, to_mode)?;
+            if reopened.as_slice() != master_key.as_slice() {
+                return Err(WalletError::CryptoError("Resealed key does not open to the same master key".into()));
+            }
+            bincode::serialize(&data).map_err(|e| WalletError::SerializationError(e.to_string()))
+        });
+        let encrypted_data = match sealed {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                outcome.encrypt_probe = ProbeStatus::Failed;
+                outcome.error = Some(e.to_string());
+                return (outcome, None);
+            }
+        };
+        outcome.encrypt_probe = ProbeStatus::Passed;
+
+        if !outcome.blockers.is_empty() {
+            outcome.error = Some(format!("Mode change blocked: {}", outcome.blockers.join(", ")));
+            return (outcome, None);
+        }
+        (outcome, Some(RewrappedEnvelope { encrypted_data, quantum_safe: to_mode == EnvelopeMode::Quantum }))
+    }
+}
+
+fn envelope_key(kek: &[u8; 32], salt: &[u8], info: &[u8]) -> Result<Zeroizing<[u8; 32]>, WalletError> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(salt), kek)
+        .expand(info, key.as_mut())
+        .map_err(|e| WalletError::CryptoError(format!("Failed to derive envelope key: {}", e)))?;
+    Ok(key)
+}
+
+/// Opens the sealed master key; classical envelopes from before the v2 AAD
+/// are accepted too.
+fn unseal(
+    quantum: &QuantumSafeEncryption,
+    data: &SecureWalletData,
+    kek: &[u8; 32],
+    mode: EnvelopeMode,
+) -> Result<Zeroizing<Vec<u8>>, WalletError> {
+    let not_sealed = || WalletError::CryptoError("Master key is not sealed under this KEK".into());
+    match mode {
+        EnvelopeMode::Quantum => {
+            let key = envelope_key(kek, &data.salt, &data.info.hkdf_info_v2())?;
+            quantum.decrypt(&data.encrypted_master_key, key.as_slice()).map_err(|_| not_sealed())
+        }
+        EnvelopeMode::Classical => {
+            if data.nonce.len() != GCM_NONCE_LEN {
+                return Err(not_sealed());
+            }
+            #[allow(deprecated)]
+            let nonce = aes_gcm::aead::Nonce::<Aes256Gcm>::from_slice(&data.nonce);
+            for info in [data.info.hkdf_info_v2(), data.info.hkdf_info_v1()] {
+                let key = envelope_key(kek, &data.salt, &info)?;
+                let cipher = Aes256Gcm::new_from_slice(key.as_slice())
+                    .map_err(|e| WalletError::CryptoError(format!("Failed to init AES cipher: {}", e)))?;
+                if let Ok(plain) = cipher.decrypt(nonce, Payload { msg: &data.encrypted_master_key, aad: &info }) {
+                    return Ok(Zeroizing::new(plain));
+                }
+            }
+            Err(not_sealed())
+        }
+    }
+}
+
+/// Seals `master_key` into `data` under `kek` with a fresh salt (and nonce),
+/// always with the v2 AAD.
+fn seal(
+    quantum: &QuantumSafeEncryption,
+    data: &mut SecureWalletData,
+    master_key: &[u8],
+    kek: &[u8; 32],
+    mode: EnvelopeMode,
+) -> Result<(), WalletError> {
+    let mut salt = vec![0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    let info = data.info.hkdf_info_v2();
+    let key = envelope_key(kek, &salt, &info)?;
+
+    let (encrypted, nonce) = match mode {
+        EnvelopeMode::Quantum => {
+            let sealed = quantum
+                .encrypt(master_key, key.as_slice())
+                .map_err(|e| WalletError::CryptoError(e.to_string()))?;
+            (sealed.to_vec(), Vec::new())
+        }
+        EnvelopeMode::Classical => {
+            let mut nonce = vec![0u8; GCM_NONCE_LEN];
+            rand::rngs::OsRng.fill_bytes(&mut nonce);
+            let cipher = Aes256Gcm::new_from_slice(key.as_slice())
+                .map_err(|e| WalletError::CryptoError(format!("Failed to init AES cipher: {}", e)))?;
+            #[allow(deprecated)]
+            let gcm_nonce = aes_gcm::aead::Nonce::<Aes256Gcm>::from_slice(&nonce);
+            let sealed = cipher
+                .encrypt(gcm_nonce, Payload { msg: master_key, aad: &info })
+                .map_err(|e| WalletError::CryptoError(format!("AES encrypt failed: {}", e)))?;
+            (sealed, nonce)
+        }
+    };
+    data.encrypted_master_key = encrypted;
+    data.salt = salt;
+    data.nonce = nonce;
+    data.info.quantum_safe = mode == EnvelopeMode::Quantum;
+    Ok(())
+}
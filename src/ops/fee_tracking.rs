@@ -0,0 +1,287 @@
+//! src/ops/fee_tracking.rs
+//!
+//! Background jobs behind fee analytics:
+//!
+//! * [`ConfirmationPoller`] fetches receipts for broadcast sends, fills in gas
+//!   used / effective price on their fee rows and moves the transaction
+//!   record out of `pending`. With [`PendingExpiry`] attached, each cycle
+//!   then expires sends stuck past their network's deadline. Networks under
+//!   `block_tracking` are left to [`BlockTracker`](crate::ops::block_tracking::BlockTracker),
+//!   which applies receipts the same way through [`apply_receipt`].
+//! * [`GasPriceSampler`] stores the gas oracle's price per network so our fee
+//!   choices can be compared with the market at the time.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use ethers::types::{TransactionReceipt, H256, U256};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::blockchain::gas_oracle::GasOracle;
+use crate::core::config::FeeTrackingConfig;
+use crate::intents::BroadcastChain;
+use crate::ops::jobs::{Job, Schedule};
+use crate::ops::leases::LeaderLease;
+use crate::ops::tx_expiry::PendingExpiry;
+use crate::storage::{FeeRecord, WalletStorage};
+use crate::token_registry::settle_delivery;
+
+/// Scheduler lease job names
+pub const FEE_CONFIRMATIONS_JOB: &str = "fee_confirmations";
+pub const GAS_SAMPLES_JOB: &str = "gas_samples";
+
+/// Receipts looked up per poll
+const CONFIRMATION_BATCH: i64 = 200;
+
+fn clamp_u64(value: U256) -> u64 {
+    value.min(U256::from(i64::MAX as u64)).as_u64()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfirmationReport {
+    pub checked: usize,
+    /// Mined with status 1
+    pub confirmed: usize,
+    /// Mined but reverted
+    pub reverted: usize,
+    pub errors: usize,
+}
+
+pub struct ConfirmationPoller {
+    config: FeeTrackingConfig,
+    storage: Arc<WalletStorage>,
+    chain: Arc<dyn BroadcastChain>,
+    lease: Option<LeaderLease>,
+    expiry: Option<PendingExpiry>,
+}
+
+impl ConfirmationPoller {
+    pub fn new(config: FeeTrackingConfig, storage: Arc<WalletStorage>, chain: Arc<dyn BroadcastChain>) -> Self {
+        Self { config, storage, chain, lease: None, expiry: None }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.confirmation_interval_secs.max(1))
+    }
+
+    pub fn with_lease(mut self, lease: LeaderLease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    pub fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// Runs `expiry` after the receipts of each cycle; ignored when no
+    /// network has a deadline.
+    pub fn with_expiry(mut self, expiry: PendingExpiry) -> Self {
+        self.expiry = (!expiry.is_empty()).then_some(expiry);
+        self
+    }
+
+    pub fn expiry(&self) -> Option<&PendingExpiry> {
+        self.expiry.as_ref()
+    }
+
+    /// One pass over fee rows still waiting for a receipt.
+    pub async fn run_once(&self) -> ConfirmationReport {
+        let mut report = ConfirmationReport::default();
+        let since = Utc::now() - chrono::Duration::hours(self.config.confirmation_max_age_hours as i64);
+        let skip = &self.config.block_tracking.networks;
+        let pending = match self.storage.unconfirmed_fee_records_except(skip, since, CONFIRMATION_BATCH).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!("fee confirmations: failed to list pending sends: {}", e);
+                report.errors += 1;
+                return report;
+            }
+        };
+        report.checked = pending.len();
+        for record in &pending {
+            match self.confirm(record).await {
+                Ok(Some(true)) => report.confirmed += 1,
+                Ok(Some(false)) => report.reverted += 1,
+                Ok(None) => {}
+                Err(e) => {
+                    warn!("fee confirmations: {} on {}: {}", record.tx_hash, record.network, e);
+                    report.errors += 1;
+                }
+            }
+        }
+        if report.confirmed + report.reverted > 0 {
+            info!("fee confirmations: {} confirmed, {} reverted", report.confirmed, report.reverted);
+        }
+        report
+    }
+
+    /// `Some(success)` once mined, `None` while still pending.
+    async fn confirm(&self, record: &FeeRecord) -> anyhow::Result<Option<bool>> {
+        let hash: H256 = record.tx_hash.parse().map_err(|_| anyhow::anyhow!("invalid transaction hash"))?;
+        let Some(receipt) = self.chain.transaction_receipt(&record.network, hash).await? else {
+            return Ok(None);
+        };
+        apply_receipt(&self.storage, record, &receipt).await.map(Some)
+    }
+}
+
+/// Completes the fee row of `record` from its receipt, moves the
+/// transaction out of `pending` (or `expired`), stores the fee actually paid
+/// and the block on the transaction record and settles its token delivery
+/// check, if any. Returns whether it succeeded.
+pub(crate) async fn apply_receipt(
+    storage: &WalletStorage,
+    record: &FeeRecord,
+    receipt: &TransactionReceipt,
+) -> anyhow::Result<bool> {
+    let gas_used = receipt.gas_used.map(clamp_u64).unwrap_or_default();
+    // 不返回 effectiveGasPrice 的旧节点：legacy transaction按报价计
+    let effective = receipt
+        .effective_gas_price
+        .map(clamp_u64)
+        .or(record.gas_price.map(|p| p as u64))
+        .unwrap_or_default();
+    storage.complete_fee_record(&record.network, &record.tx_hash, gas_used, effective).await?;
+
+    let success = receipt.status.is_none_or(|s| s.as_u64() == 1);
+    // a reverted send was mined but never confirmed
+    let confirmed_at = success.then(Utc::now);
+    if let Some(tx) = storage.transaction_by_hash(&record.tx_hash).await? {
+        // an expired send mined late is moved too; storage books the correction
+        if tx.status == "pending" || tx.status == "expired" {
+            let status = if success { "confirmed" } else { "failed" };
+            storage.update_transaction_status(&tx.id, status, confirmed_at).await?;
+        }
+    }
+    // a reverted send paid for its gas as well
+    if let Some(block) = receipt.block_number {
+        let paid = U256::from(gas_used).saturating_mul(U256::from(effective));
+        storage
+            .finalize_transaction(&record.tx_hash, &ethers::utils::format_ether(paid), confirmed_at, block.as_u64())
+            .await?;
+    }
+    // the fee row is complete either way, so a failed check is not retried
+    if let Err(e) = settle_delivery(storage, &record.network, receipt).await {
+        warn!("token delivery check of {} failed: {}", record.tx_hash, e);
+    }
+    Ok(success)
+}
+
+#[async_trait]
+impl Job for ConfirmationPoller {
+    fn name(&self) -> &str {
+        FEE_CONFIRMATIONS_JOB
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: self.interval(), immediate: true }
+    }
+
+    fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// Fails when lookups errored and no receipt came back at all, or when
+    /// the expiry pass only produced errors.
+    async fn run(&self, _cancel: CancellationToken) -> anyhow::Result<()> {
+        let report = self.run_once().await;
+        debug!("fee confirmation cycle: {:?}", report);
+        if report.errors > 0 && report.confirmed + report.reverted == 0 {
+            anyhow::bail!("fee confirmation cycle failed ({} errors)", report.errors);
+        }
+        if let Some(expiry) = &self.expiry {
+            let expired = expiry.run_once().await;
+            debug!("pending expiry pass: {:?}", expired);
+            if expired.errors > 0 && expired.expired + expired.still_known == 0 {
+                anyhow::bail!("pending expiry pass failed ({} errors)", expired.errors);
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct GasPriceSampler {
+    config: FeeTrackingConfig,
+    networks: Vec<String>,
+    oracle: Arc<dyn GasOracle>,
+    storage: Arc<WalletStorage>,
+    lease: Option<LeaderLease>,
+}
+
+impl GasPriceSampler {
+    /// `configured` are the networks of the blockchain config; the
+    /// `fee_tracking.networks` list narrows them when set.
+    pub fn new(
+        config: FeeTrackingConfig,
+        configured: impl IntoIterator<Item = String>,
+        oracle: Arc<dyn GasOracle>,
+        storage: Arc<WalletStorage>,
+    ) -> Self {
+        let mut networks: Vec<String> = configured
+            .into_iter()
+            .filter(|n| config.networks.is_empty() || config.networks.contains(n))
+            .collect();
+        networks.sort();
+        Self { config, networks, oracle, storage, lease: None }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.gas_sample_interval_secs.max(1))
+    }
+
+    pub fn with_lease(mut self, lease: LeaderLease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    pub fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// Samples every supported network once; returns samples stored.
+    pub async fn run_once(&self) -> usize {
+        let now = Utc::now();
+        let mut recorded = 0;
+        for network in self.networks.iter().filter(|n| self.oracle.supports(n)) {
+            let price = match self.oracle.gas_price(network).await {
+                Ok(price) => clamp_u64(price),
+                Err(e) => {
+                    warn!("gas samples: {}: {}", network, e);
+                    continue;
+                }
+            };
+            match self.storage.record_gas_price_sample(network, price, now).await {
+                Ok(()) => recorded += 1,
+                Err(e) => warn!("gas samples: {}: {}", network, e),
+            }
+        }
+        recorded
+    }
+}
+
+#[async_trait]
+impl Job for GasPriceSampler {
+    fn name(&self) -> &str {
+        GAS_SAMPLES_JOB
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: self.interval(), immediate: true }
+    }
+
+    fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    async fn run(&self, _cancel: CancellationToken) -> anyhow::Result<()> {
+        let recorded = self.run_once().await;
+        debug!("gas price samples recorded: {}", recorded);
+        if recorded == 0 && self.networks.iter().any(|n| self.oracle.supports(n)) {
+            anyhow::bail!("no gas price could be sampled");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+//! Process-wide panic reporting and error tallies.
+//!
+//! [`install_panic_hook`] chains a hook in front of the default one. Every
+//! panic, caught or not, becomes a [`PanicReport`]: the sanitized message,
+//! where it happened, a hash of the backtrace and the name of the task that
+//! panicked. Tasks get names by being started with [`spawn_named`] (or run
+//! inside [`with_task_name`], which is how job runs are labelled).
+//!
+//! The hook itself never touches the database. It bumps
+//! `process_panics_total` and queues the report for the writer task started
+//! by [`attach`], which stores it in `process_incidents`. Before `attach`,
+//! after [`detach`], when the queue is gone or when the insert fails, the
+//! report is written to stderr as one JSON line instead, so a panic is never
+//! lost just because the database is what broke.
+//!
+//! [`ErrorTally`] keeps per-minute counts of failed requests by
+//! [`ErrorClass`] for the last day, for `GET /api/admin/incidents`.
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::Write as _;
+use std::sync::{Arc, Once};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::core::error_class::ErrorClass;
+use crate::monitoring::WalletMetrics;
+use crate::security::error_sanitizer::sanitize_error_message;
+use crate::storage::{NewIncident, WalletStorage};
+
+/// Task name recorded for panics outside any named task.
+pub const UNNAMED_TASK: &str = "unnamed";
+/// How far back [`ErrorTally`] remembers.
+pub const MAX_TALLY_WINDOW_SECS: u64 = 24 * 60 * 60;
+/// Hex digits of the backtrace hash that are kept.
+const BACKTRACE_HASH_LEN: usize = 16;
+
+tokio::task_local! {
+    static TASK_NAME: Cow<'static, str>;
+}
+
+/// `tokio::spawn` with a name that panics inside the task are reported under.
+pub fn spawn_named<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(TASK_NAME.scope(Cow::Borrowed(name), future))
+}
+
+/// Runs `future` under `name` without spawning; the innermost name wins.
+pub fn with_task_name<F: Future>(name: impl Into<Cow<'static, str>>, future: F) -> impl Future<Output = F::Output> {
+    TASK_NAME.scope(name.into(), future)
+}
+
+/// Name of the task currently running, if it has one.
+pub fn current_task_name() -> Option<String> {
+    TASK_NAME.try_with(|name| name.to_string()).ok()
+}
+
+/// The message of a panic payload, for `&str` and `String` payloads.
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// One panic, as queued by the hook.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PanicReport {
+    pub task: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace_hash: String,
+    pub thread: Option<String>,
+    /// Unix seconds
+    pub occurred_at: i64,
+}
+
+impl PanicReport {
+    pub fn as_incident(&self) -> NewIncident<'_> {
+        NewIncident {
+            task: &self.task,
+            message: &self.message,
+            location: self.location.as_deref(),
+            backtrace_hash: &self.backtrace_hash,
+            thread: self.thread.as_deref(),
+            occurred_at: self.occurred_at,
+        }
+    }
+}
+
+struct Sink {
+    reports: mpsc::UnboundedSender<PanicReport>,
+    metrics: Arc<WalletMetrics>,
+}
+
+static SINK: RwLock<Option<Sink>> = parking_lot::const_rwlock(None);
+static INSTALL: Once = Once::new();
+
+/// Installs the panic hook once per process; later calls do nothing. The
+/// previously installed hook still runs after the report is taken.
+pub fn install_panic_hook() {
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let location = info.location().map(|l| l.to_string());
+            report(capture(panic_message(info.payload()), location));
+            previous(info);
+        }));
+    });
+}
+
+/// Routes reports to `storage` from now on and returns the writer task.
+/// Attaching again replaces the previous destination.
+pub fn attach(storage: Arc<WalletStorage>, metrics: Arc<WalletMetrics>) -> JoinHandle<()> {
+    let (reports, mut queue) = mpsc::unbounded_channel::<PanicReport>();
+    *SINK.write() = Some(Sink { reports, metrics });
+    spawn_named("incident_writer", async move {
+        while let Some(report) = queue.recv().await {
+            if let Err(e) = storage.record_incident(&report.as_incident()).await {
+                write_stderr(&report, Some(&e.to_string()));
+            }
+        }
+    })
+}
+
+/// Sends reports back to stderr. The writer finishes what is queued and exits.
+pub fn detach() {
+    *SINK.write() = None;
+}
+
+fn capture(message: &str, location: Option<String>) -> PanicReport {
+    let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+    let mut backtrace_hash = hex::encode(Sha256::digest(backtrace.as_bytes()));
+    backtrace_hash.truncate(BACKTRACE_HASH_LEN);
+    PanicReport {
+        task: current_task_name().unwrap_or_else(|| UNNAMED_TASK.to_string()),
+        message: sanitize_error_message(message),
+        location,
+        backtrace_hash,
+        thread: std::thread::current().name().map(str::to_string),
+        occurred_at: chrono::Utc::now().timestamp(),
+    }
+}
+
+fn report(report: PanicReport) {
+    // `try_read`: a panic while the sink is being swapped must not deadlock
+    let unsent = match SINK.try_read().as_deref() {
+        Some(Some(sink)) => {
+            sink.metrics.record_panic(&report.task);
+            sink.reports.send(report).err().map(|e| e.0)
+        }
+        _ => Some(report),
+    };
+    if let Some(report) = unsent {
+        write_stderr(&report, None);
+    }
+}
+
+/// Last resort: one JSON line on stderr. Write errors are ignored, since
+/// `eprintln!` would panic inside the panic hook.
+fn write_stderr(report: &PanicReport, persist_error: Option<&str>) {
+    let line = serde_json::json!({ "incident": report, "persist_error": persist_error });
+    let _ = writeln!(std::io::stderr(), "{}", line);
+}
+
+/// Failed requests of one class within a window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorClassCount {
+    pub class: ErrorClass,
+    pub count: u64,
+}
+
+type MinuteCounts = [u64; ErrorClass::ALL.len()];
+
+/// Per-minute counts of failed requests by class, for the last
+/// [`MAX_TALLY_WINDOW_SECS`]. Process-local; each instance reports its own.
+#[derive(Debug, Default)]
+pub struct ErrorTally {
+    /// `(unix minute, counts)`, oldest first
+    minutes: Mutex<VecDeque<(i64, MinuteCounts)>>,
+}
+
+impl ErrorTally {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, class: ErrorClass, now: i64) {
+        let minute = now.div_euclid(60);
+        let mut minutes = self.minutes.lock();
+        // a clock that stepped back keeps counting into the newest bucket
+        if minutes.back().is_none_or(|(m, _)| *m < minute) {
+            minutes.push_back((minute, [0; ErrorClass::ALL.len()]));
+        }
+        if let Some((_, counts)) = minutes.back_mut() {
+            counts[class.index()] += 1;
+        }
+        let oldest = minute - (MAX_TALLY_WINDOW_SECS / 60) as i64;
+        while minutes.front().is_some_and(|(m, _)| *m <= oldest) {
+            minutes.pop_front();
+        }
+    }
+
+    /// Classes seen in the `window_secs` before `now`, most frequent first.
+    /// The window is counted in whole minutes and capped at a day.
+    pub fn top(&self, now: i64, window_secs: u64) -> Vec<ErrorClassCount> {
+        let from = (now - window_secs.min(MAX_TALLY_WINDOW_SECS) as i64).div_euclid(60);
+        let mut totals: MinuteCounts = [0; ErrorClass::ALL.len()];
+        for (_, counts) in self.minutes.lock().iter().filter(|(m, _)| *m >= from) {
+            for (total, count) in totals.iter_mut().zip(counts) {
+                *total += count;
+            }
+        }
+        let mut top: Vec<ErrorClassCount> = ErrorClass::ALL
+            .iter()
+            .zip(totals)
+            .filter(|(_, count)| *count > 0)
+            .map(|(class, count)| ErrorClassCount { class: *class, count })
+            .collect();
+        top.sort_by(|a, b| b.count.cmp(&a.count).then(a.class.cmp(&b.class)));
+        top
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tally_windows_and_expires() {
+        let tally = ErrorTally::new();
+        let now = 1_700_000_000;
+        tally.record(ErrorClass::Database, now - 2 * 3600);
+        for _ in 0..3 {
+            tally.record(ErrorClass::Validation, now - 30);
+        }
+        tally.record(ErrorClass::Database, now);
+
+        let last_hour = tally.top(now, 3600);
+        assert_eq!(
+            last_hour,
+            vec![
+                ErrorClassCount { class: ErrorClass::Validation, count: 3 },
+                ErrorClassCount { class: ErrorClass::Database, count: 1 },
+            ]
+        );
+        assert_eq!(tally.top(now, 3 * 3600)[1], ErrorClassCount { class: ErrorClass::Database, count: 2 });
+
+        // a day later only the newest minute is left
+        tally.record(ErrorClass::Timeout, now + MAX_TALLY_WINDOW_SECS as i64);
+        assert_eq!(tally.minutes.lock().len(), 1);
+        assert_eq!(tally.top(now + MAX_TALLY_WINDOW_SECS as i64, MAX_TALLY_WINDOW_SECS).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_named_sets_the_task_name() {
+        assert_eq!(current_task_name(), None);
+        let name = spawn_named("probe", async { current_task_name() }).await.unwrap();
+        assert_eq!(name.as_deref(), Some("probe"));
+        let nested = spawn_named("outer", with_task_name("job:inner".to_string(), async { current_task_name() }));
+        assert_eq!(nested.await.unwrap().as_deref(), Some("job:inner"));
+    }
+}
@@ -0,0 +1,592 @@
+//! src/ops/jobs.rs
+//!
+//! Supervised background jobs.
+//!
+//! A [`Job`] says what it is called, when it runs and how to run once; the
+//! [`JobRunner`] owns one supervisor task per job. The supervisor waits for
+//! the schedule, skips the tick in maintenance mode or when another instance
+//! holds the job's lease, and runs the job with panics caught. A run that
+//! errors or panics is retried after an exponential backoff instead of at the
+//! next scheduled time, and every outcome lands in the job's [`JobState`], so
+//! a job that keeps failing (or whose supervisor died) shows up in
+//! `GET /api/admin/jobs` instead of going quiet.
+//!
+//! Scheduled and manual runs of one job never overlap. Shutdown cancels the
+//! token handed to every run, waits up to the drain timeout and aborts what
+//! is left, then releases the jobs' leases.
+
+use std::collections::BTreeMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveTime, Utc};
+use futures::FutureExt;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use crate::blockchain::rpc_limits;
+use crate::core::clock::{system_clock, Clock};
+use crate::core::config::JobsConfig;
+use crate::ops::incidents;
+use crate::ops::leases::LeaderLease;
+use crate::ops::maintenance::MaintenanceMode;
+use crate::security::error_sanitizer::sanitize_error_message;
+
+/// When a job runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    /// Every `every`, measured from the end of the previous run. With
+    /// `immediate` the first run is at startup, otherwise one period later.
+    Every { every: Duration, immediate: bool },
+    /// Once a day at `hour:minute` UTC
+    DailyAt { hour: u32, minute: u32 },
+}
+
+impl Schedule {
+    /// Time from `now` until the next run; `first` is the run after startup.
+    pub fn next_delay(&self, now: DateTime<Utc>, first: bool) -> Duration {
+        match *self {
+            Schedule::Every { every, immediate } => {
+                if first && immediate {
+                    Duration::ZERO
+                } else {
+                    every
+                }
+            }
+            Schedule::DailyAt { hour, minute } => {
+                let at = NaiveTime::from_hms_opt(hour.min(23), minute.min(59), 0).unwrap_or_default();
+                let mut next = now.date_naive().and_time(at).and_utc();
+                if next <= now {
+                    next += chrono::Duration::days(1);
+                }
+                (next - now).to_std().unwrap_or_default()
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Schedule::Every { every, .. } => format!("every {}s", every.as_secs()),
+            Schedule::DailyAt { hour, minute } => format!("daily at {:02}:{:02} UTC", hour, minute),
+        }
+    }
+}
+
+/// A background task driven by [`JobRunner`]
+#[async_trait]
+pub trait Job: Send + Sync {
+    /// Unique name; also the path segment of `POST /api/admin/jobs/:name/run`
+    fn name(&self) -> &str;
+
+    fn schedule(&self) -> Schedule;
+
+    /// Scheduled runs only happen on the instance holding this lease
+    fn lease(&self) -> Option<&LeaderLease> {
+        None
+    }
+
+    /// Skip runs while maintenance mode is on
+    fn pauses_in_maintenance(&self) -> bool {
+        true
+    }
+
+    /// How far the run in progress has got, for jobs that can tell
+    fn progress(&self) -> Option<JobProgress> {
+        None
+    }
+
+    /// One run. `cancel` fires on shutdown; long runs should stop early.
+    async fn run(&self, cancel: CancellationToken) -> anyhow::Result<()>;
+}
+
+/// Progress of a running job, as reported by [`Job::progress`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobProgress {
+    /// What the run is doing, e.g. the network being read
+    pub stage: String,
+    pub done: u64,
+    /// `None` while the amount of work is not known yet
+    pub total: Option<u64>,
+}
+
+/// Health of one job as reported by `GET /api/admin/jobs`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobState {
+    pub name: String,
+    pub schedule: String,
+    /// A run is in progress
+    pub running: bool,
+    /// Unix seconds
+    pub last_run_at: Option<i64>,
+    pub last_success_at: Option<i64>,
+    /// Most recent failure, kept after later successes
+    pub last_error: Option<String>,
+    pub last_error_at: Option<i64>,
+    pub consecutive_failures: u32,
+    pub runs: u64,
+    pub failures: u64,
+    /// Runs that panicked (each was contained and retried)
+    pub panics: u64,
+    /// The supervisor task is alive
+    pub alive: bool,
+    /// Alive and below the configured consecutive-failure threshold
+    pub healthy: bool,
+    /// Progress of the run in progress, for jobs that report it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<JobProgress>,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum JobRunError {
+    #[error("no job named {0}")]
+    NotFound(String),
+    #[error("job {0} is already running")]
+    AlreadyRunning(String),
+    #[error("maintenance mode is enabled")]
+    Maintenance,
+    #[error("shutting down")]
+    ShuttingDown,
+}
+
+struct JobSlot {
+    job: Arc<dyn Job>,
+    state: Mutex<JobState>,
+    /// Held for the duration of a run; scheduled runs queue, manual ones fail fast
+    running: tokio::sync::Mutex<()>,
+    supervisor: Mutex<Option<JoinHandle<()>>>,
+}
+
+enum RunOutcome {
+    Succeeded,
+    Failed(String),
+    Panicked(String),
+}
+
+pub struct JobRunner {
+    config: JobsConfig,
+    maintenance: Arc<MaintenanceMode>,
+    clock: Arc<dyn Clock>,
+    jobs: Mutex<BTreeMap<String, Arc<JobSlot>>>,
+    cancel: CancellationToken,
+}
+
+impl JobRunner {
+    pub fn new(config: JobsConfig, maintenance: Arc<MaintenanceMode>) -> Self {
+        Self {
+            config,
+            maintenance,
+            clock: system_clock(),
+            jobs: Mutex::new(BTreeMap::new()),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Stamps job states with `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Adds `job`; it is scheduled by the next [`start`](Self::start). A job
+    /// with the same name replaces one that was never started.
+    pub fn register(&self, job: Arc<dyn Job>) {
+        let name = job.name().to_string();
+        let state = JobState { name: name.clone(), schedule: job.schedule().describe(), ..Default::default() };
+        let mut jobs = self.jobs.lock();
+        if jobs.get(&name).is_some_and(|slot| slot.supervisor.lock().is_some()) {
+            warn!("job {} is already running; ignoring the new registration", name);
+            return;
+        }
+        jobs.insert(
+            name,
+            Arc::new(JobSlot {
+                job,
+                state: Mutex::new(state),
+                running: tokio::sync::Mutex::new(()),
+                supervisor: Mutex::new(None),
+            }),
+        );
+    }
+
+    /// Spawns a supervisor for every registered job that has none yet.
+    pub fn start(self: &Arc<Self>) {
+        for slot in self.jobs.lock().values() {
+            let mut supervisor = slot.supervisor.lock();
+            if supervisor.is_none() && !self.cancel.is_cancelled() {
+                info!("job {} scheduled {}", slot.job.name(), slot.job.schedule().describe());
+                *supervisor = Some(incidents::spawn_named("job_supervisor", self.clone().supervise(slot.clone())));
+            }
+        }
+    }
+
+    /// Every job's state, by name.
+    pub fn states(&self) -> Vec<JobState> {
+        let slots: Vec<Arc<JobSlot>> = self.jobs.lock().values().cloned().collect();
+        slots.iter().map(|slot| self.snapshot(slot)).collect()
+    }
+
+    pub fn state(&self, name: &str) -> Option<JobState> {
+        let slot = self.jobs.lock().get(name).cloned()?;
+        Some(self.snapshot(&slot))
+    }
+
+    fn snapshot(&self, slot: &JobSlot) -> JobState {
+        let mut state = slot.state.lock().clone();
+        state.alive = slot.supervisor.lock().as_ref().is_some_and(|handle| !handle.is_finished());
+        state.healthy = state.alive && state.consecutive_failures < self.config.unhealthy_after_failures.max(1);
+        if state.running {
+            state.progress = slot.job.progress();
+        }
+        state
+    }
+
+    /// Runs `name` now, outside its schedule, and returns its state after the
+    /// run. The lease is not consulted: the operator asked this instance.
+    pub async fn run_now(&self, name: &str) -> Result<JobState, JobRunError> {
+        let slot = self.jobs.lock().get(name).cloned().ok_or_else(|| JobRunError::NotFound(name.to_string()))?;
+        if self.cancel.is_cancelled() {
+            return Err(JobRunError::ShuttingDown);
+        }
+        if slot.job.pauses_in_maintenance() && self.maintenance.is_enabled() {
+            return Err(JobRunError::Maintenance);
+        }
+        let guard = slot.running.try_lock().map_err(|_| JobRunError::AlreadyRunning(name.to_string()))?;
+        info!("job {} triggered manually", name);
+        self.execute(&slot).await;
+        drop(guard);
+        Ok(self.snapshot(&slot))
+    }
+
+    /// Cancels every job, waits up to `drain` for runs in progress and aborts
+    /// the rest, then releases the jobs' leases. `false` if anything had to
+    /// be aborted.
+    pub async fn shutdown(&self, drain: Duration) -> bool {
+        self.cancel.cancel();
+        let slots: Vec<Arc<JobSlot>> = self.jobs.lock().values().cloned().collect();
+        let deadline = tokio::time::Instant::now() + drain;
+        let mut drained = true;
+        for slot in &slots {
+            let Some(mut handle) = slot.supervisor.lock().take() else { continue };
+            if tokio::time::timeout_at(deadline, &mut handle).await.is_err() {
+                warn!("job {} did not stop within {:?}; aborting", slot.job.name(), drain);
+                handle.abort();
+                slot.state.lock().running = false;
+                drained = false;
+            }
+        }
+        for slot in &slots {
+            if let Some(lease) = slot.job.lease() {
+                lease.release().await;
+            }
+        }
+        drained
+    }
+
+    /// Backoff before retry number `failures` (1-based)
+    fn restart_delay(&self, failures: u32) -> Duration {
+        let base = Duration::from_secs(self.config.restart_base_secs.max(1));
+        let max = Duration::from_secs(self.config.restart_max_secs.max(self.config.restart_base_secs).max(1));
+        base.saturating_mul(1u32 << failures.saturating_sub(1).min(16)).min(max)
+    }
+
+    async fn supervise(self: Arc<Self>, slot: Arc<JobSlot>) {
+        let name = slot.job.name().to_string();
+        let schedule = slot.job.schedule();
+        let mut first = true;
+        loop {
+            let failures = slot.state.lock().consecutive_failures;
+            let delay = if failures > 0 {
+                self.restart_delay(failures)
+            } else {
+                schedule.next_delay(self.clock.now(), first)
+            };
+            first = false;
+            tokio::select! {
+                _ = self.cancel.cancelled() => break,
+                _ = tokio::time::sleep(delay) => {}
+            }
+            if slot.job.pauses_in_maintenance() && self.maintenance.is_enabled() {
+                debug!("job {} skipped: maintenance mode", name);
+                continue;
+            }
+            if let Some(lease) = slot.job.lease() {
+                if !lease.hold().await {
+                    debug!("job {}: held by another instance, skipping", name);
+                    continue;
+                }
+            }
+            let _guard = slot.running.lock().await;
+            if self.cancel.is_cancelled() {
+                break;
+            }
+            self.execute(&slot).await;
+        }
+        debug!("job {} stopped", name);
+    }
+
+    /// One run with the caller holding `slot.running`.
+    async fn execute(&self, slot: &JobSlot) {
+        let name = slot.job.name();
+        {
+            let mut state = slot.state.lock();
+            state.running = true;
+            state.last_run_at = Some(self.clock.now().timestamp());
+        }
+        // RPC calls made by jobs are background traffic under the outbound quotas;
+        // a panic is reported as an incident of `job:<name>` before it is caught here
+        let cancel = self.cancel.child_token();
+        let run = rpc_limits::background(cancel.clone(), slot.job.run(cancel));
+        let run = AssertUnwindSafe(incidents::with_task_name(format!("job:{}", name), run)).catch_unwind().await;
+        let outcome = match run {
+            Ok(Ok(())) => RunOutcome::Succeeded,
+            Ok(Err(e)) => RunOutcome::Failed(sanitize_error_message(&format!("{:#}", e))),
+            Err(panic) => RunOutcome::Panicked(incidents::panic_message(panic.as_ref()).to_string()),
+        };
+
+        let now = self.clock.now().timestamp();
+        let mut state = slot.state.lock();
+        state.running = false;
+        state.runs += 1;
+        match outcome {
+            RunOutcome::Succeeded => {
+                if state.consecutive_failures > 0 {
+                    info!("job {} recovered after {} failed runs", name, state.consecutive_failures);
+                }
+                state.consecutive_failures = 0;
+                state.last_success_at = Some(now);
+                return;
+            }
+            RunOutcome::Failed(message) => {
+                warn!("job {} failed: {}", name, message);
+                state.last_error = Some(message);
+            }
+            RunOutcome::Panicked(message) => {
+                error!("job {} panicked: {}", name, message);
+                state.panics += 1;
+                state.last_error = Some(format!("panicked: {}", message));
+            }
+        }
+        state.failures += 1;
+        state.consecutive_failures += 1;
+        state.last_error_at = Some(now);
+        if state.consecutive_failures == self.config.unhealthy_after_failures.max(1) {
+            error!("job {} is unhealthy: {} consecutive failures", name, state.consecutive_failures);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Fails or panics on the listed (0-based) runs, succeeds otherwise
+    struct ScriptedJob {
+        name: &'static str,
+        schedule: Schedule,
+        fail_on: Vec<usize>,
+        panic_on: Vec<usize>,
+        runs: AtomicUsize,
+    }
+
+    impl ScriptedJob {
+        fn new(name: &'static str, schedule: Schedule) -> Self {
+            Self { name, schedule, fail_on: Vec::new(), panic_on: Vec::new(), runs: AtomicUsize::new(0) }
+        }
+    }
+
+    #[async_trait]
+    impl Job for ScriptedJob {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn schedule(&self) -> Schedule {
+            self.schedule
+        }
+
+        async fn run(&self, _cancel: CancellationToken) -> anyhow::Result<()> {
+            let run = self.runs.fetch_add(1, Ordering::SeqCst);
+            if self.panic_on.contains(&run) {
+                panic!("scripted panic on run {}", run);
+            }
+            if self.fail_on.contains(&run) {
+                anyhow::bail!("scripted failure on run {}", run);
+            }
+            Ok(())
+        }
+    }
+
+    /// Sleeps for an hour; with `cooperative` it returns when cancelled
+    struct SlowJob {
+        name: &'static str,
+        cooperative: bool,
+    }
+
+    #[async_trait]
+    impl Job for SlowJob {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn schedule(&self) -> Schedule {
+            Schedule::Every { every: Duration::from_secs(3600), immediate: true }
+        }
+
+        async fn run(&self, cancel: CancellationToken) -> anyhow::Result<()> {
+            let sleep = tokio::time::sleep(Duration::from_secs(3600));
+            if self.cooperative {
+                tokio::select! {
+                    _ = cancel.cancelled() => {}
+                    _ = sleep => {}
+                }
+            } else {
+                sleep.await;
+            }
+            Ok(())
+        }
+    }
+
+    fn config() -> JobsConfig {
+        JobsConfig { restart_base_secs: 1, restart_max_secs: 8, unhealthy_after_failures: 2, drain_timeout_secs: 5 }
+    }
+
+    fn new_runner() -> Arc<JobRunner> {
+        Arc::new(JobRunner::new(config(), Arc::new(MaintenanceMode::new())))
+    }
+
+    async fn settle(by: Duration) {
+        tokio::time::sleep(by).await;
+        tokio::task::yield_now().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failures_back_off_then_recover() {
+        let runner = new_runner();
+        let mut job = ScriptedJob::new("flaky", Schedule::Every { every: Duration::from_secs(60), immediate: true });
+        job.fail_on = vec![0, 1];
+        runner.register(Arc::new(job));
+        runner.start();
+
+        settle(Duration::from_millis(10)).await;
+        let state = runner.state("flaky").unwrap();
+        assert_eq!((state.runs, state.consecutive_failures), (1, 1));
+        assert_eq!(state.last_error.as_deref(), Some("scripted failure on run 0"));
+        assert!(state.healthy);
+        assert!(state.last_success_at.is_none());
+
+        // retried after 1s rather than the 60s schedule
+        settle(Duration::from_secs(1)).await;
+        let state = runner.state("flaky").unwrap();
+        assert_eq!((state.runs, state.consecutive_failures), (2, 2));
+        assert!(!state.healthy, "two consecutive failures reach the threshold");
+        assert!(state.alive);
+
+        // second retry waits twice as long
+        settle(Duration::from_secs(1)).await;
+        assert_eq!(runner.state("flaky").unwrap().runs, 2);
+        settle(Duration::from_secs(1)).await;
+        let state = runner.state("flaky").unwrap();
+        assert_eq!((state.runs, state.consecutive_failures, state.failures), (3, 0, 2));
+        assert!(state.healthy);
+        assert!(state.last_success_at.is_some());
+        assert_eq!(state.last_error.as_deref(), Some("scripted failure on run 1"));
+
+        // back on the regular schedule
+        settle(Duration::from_secs(30)).await;
+        assert_eq!(runner.state("flaky").unwrap().runs, 3);
+        settle(Duration::from_secs(31)).await;
+        assert_eq!(runner.state("flaky").unwrap().runs, 4);
+        assert!(runner.shutdown(Duration::from_secs(1)).await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_panic_is_contained_and_restarted() {
+        let runner = new_runner();
+        let mut job = ScriptedJob::new("panicky", Schedule::Every { every: Duration::from_secs(60), immediate: true });
+        job.panic_on = vec![0];
+        runner.register(Arc::new(job));
+        runner.start();
+
+        settle(Duration::from_millis(10)).await;
+        let state = runner.state("panicky").unwrap();
+        assert_eq!(state.panics, 1);
+        assert_eq!(state.last_error.as_deref(), Some("panicked: scripted panic on run 0"));
+        assert!(state.alive, "the supervisor survives the panic");
+        assert!(!state.running);
+
+        settle(Duration::from_secs(1)).await;
+        let state = runner.state("panicky").unwrap();
+        assert_eq!((state.runs, state.consecutive_failures), (2, 0));
+        assert!(state.healthy);
+        assert!(runner.shutdown(Duration::from_secs(1)).await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_cancels_within_drain_timeout() {
+        let runner = new_runner();
+        runner.register(Arc::new(SlowJob { name: "cooperative", cooperative: true }));
+        runner.register(Arc::new(ScriptedJob::new(
+            "idle",
+            Schedule::Every { every: Duration::from_secs(600), immediate: false },
+        )));
+        runner.start();
+        settle(Duration::from_millis(10)).await;
+        assert!(runner.state("cooperative").unwrap().running);
+
+        let started = tokio::time::Instant::now();
+        assert!(runner.shutdown(Duration::from_secs(5)).await);
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(runner.states().iter().all(|s| !s.alive));
+
+        // a run that ignores cancellation is aborted at the drain deadline
+        let runner = new_runner();
+        runner.register(Arc::new(SlowJob { name: "stubborn", cooperative: false }));
+        runner.start();
+        settle(Duration::from_millis(10)).await;
+        let started = tokio::time::Instant::now();
+        assert!(!runner.shutdown(Duration::from_secs(5)).await);
+        assert_eq!(started.elapsed(), Duration::from_secs(5));
+        let state = runner.state("stubborn").unwrap();
+        assert!(!state.alive && !state.running);
+        assert_eq!(runner.run_now("stubborn").await, Err(JobRunError::ShuttingDown));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_manual_trigger_runs_outside_schedule() {
+        let runner = new_runner();
+        let maintenance = runner.maintenance.clone();
+        runner.register(Arc::new(ScriptedJob::new("nightly", Schedule::DailyAt { hour: 3, minute: 0 })));
+        runner.start();
+        settle(Duration::from_secs(60)).await;
+        assert_eq!(runner.state("nightly").unwrap().runs, 0);
+
+        let state = runner.run_now("nightly").await.unwrap();
+        assert_eq!(state.runs, 1);
+        assert!(state.last_success_at.is_some());
+        assert!(state.alive);
+
+        assert_eq!(runner.run_now("hourly").await, Err(JobRunError::NotFound("hourly".to_string())));
+        maintenance.enable("upgrade");
+        assert_eq!(runner.run_now("nightly").await, Err(JobRunError::Maintenance));
+        assert!(runner.shutdown(Duration::from_secs(1)).await);
+    }
+
+    #[test]
+    fn test_daily_schedule_delay() {
+        let schedule = Schedule::DailyAt { hour: 3, minute: 30 };
+        let before = Utc.with_ymd_and_hms(2024, 5, 1, 1, 0, 0).unwrap();
+        assert_eq!(schedule.next_delay(before, true), Duration::from_secs(150 * 60));
+        let after = Utc.with_ymd_and_hms(2024, 5, 1, 3, 30, 0).unwrap();
+        assert_eq!(schedule.next_delay(after, false), Duration::from_secs(86_400));
+        let every = Schedule::Every { every: Duration::from_secs(30), immediate: true };
+        assert_eq!(every.next_delay(before, true), Duration::ZERO);
+        assert_eq!(every.next_delay(before, false), Duration::from_secs(30));
+    }
+}
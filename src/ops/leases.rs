@@ -0,0 +1,105 @@
+//! src/ops/leases.rs
+//!
+//! Leader leases for background jobs when several server instances share one
+//! database.
+//!
+//! Every instance runs the job's ticker, but a tick only does work on the
+//! instance holding the job's named lease in `distributed_locks`. The holder
+//! renews on each tick; if it dies the lease runs out after `ttl` and the
+//! next instance to tick takes over.
+
+use std::borrow::Cow;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::ops::incidents;
+use crate::storage::WalletStorage;
+
+/// Lock name prefix for scheduler leases
+pub const SCHEDULER_LOCK_PREFIX: &str = "scheduler:";
+
+#[derive(Clone)]
+pub struct LeaderLease {
+    storage: Arc<WalletStorage>,
+    name: String,
+    ttl: Duration,
+}
+
+impl LeaderLease {
+    pub fn new(storage: Arc<WalletStorage>, name: impl Into<String>, ttl: Duration) -> Self {
+        Self { storage, name: name.into(), ttl }
+    }
+
+    /// Lease for a job ticking every `interval`: it outlives one missed tick
+    /// by `grace`, so a live holder never loses it between ticks.
+    pub fn for_scheduler(storage: Arc<WalletStorage>, job: &str, interval: Duration, grace: Duration) -> Self {
+        Self::new(storage, format!("{}{}", SCHEDULER_LOCK_PREFIX, job), interval + grace)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Renews the lease if this instance holds it, otherwise tries to take it.
+    /// Database errors count as "not held": skipping a tick is safe, running
+    /// twice is not.
+    pub async fn hold(&self) -> bool {
+        let held = match self.storage.renew_lock(&self.name, self.ttl).await {
+            Ok(true) => Ok(true),
+            Ok(false) => self.storage.try_acquire_lock(&self.name, self.ttl).await.inspect(|&acquired| {
+                if acquired {
+                    info!("{}: lease acquired by instance {}", self.name, self.storage.instance_id());
+                }
+            }),
+            Err(e) => Err(e),
+        };
+        held.unwrap_or_else(|e| {
+            warn!("{}: lease check failed: {}", self.name, e);
+            false
+        })
+    }
+
+    /// Gives the lease up so another instance can take over without waiting
+    /// for it to expire (graceful shutdown).
+    pub async fn release(&self) {
+        if let Err(e) = self.storage.release_lock(&self.name).await {
+            warn!("{}: failed to release lease: {}", self.name, e);
+        }
+    }
+}
+
+/// Runs `tick` every `interval` until aborted; with a lease, only on the
+/// instance that holds it.
+pub fn spawn_periodic<F, Fut>(
+    interval: Duration,
+    skip_first_tick: bool,
+    lease: Option<LeaderLease>,
+    tick: F,
+) -> JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    let task = lease.as_ref().map_or(Cow::Borrowed("periodic"), |lease| Cow::Owned(lease.name().to_string()));
+    tokio::spawn(incidents::with_task_name(task, async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        if skip_first_tick {
+            ticker.tick().await;
+        }
+        loop {
+            ticker.tick().await;
+            if let Some(lease) = &lease {
+                if !lease.hold().await {
+                    debug!("{}: held by another instance, skipping tick", lease.name());
+                    continue;
+                }
+            }
+            tick().await;
+        }
+    }))
+}
@@ -0,0 +1,54 @@
+//! src/ops/maintenance.rs
+//!
+//! Process-wide maintenance switch. Background jobs check it before doing
+//! work so operators can quiesce the service without stopping it.
+
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Debug, Default)]
+pub struct MaintenanceMode {
+    enabled: AtomicBool,
+    reason: RwLock<Option<String>>,
+}
+
+impl MaintenanceMode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enable(&self, reason: impl Into<String>) {
+        *self.reason.write() = Some(reason.into());
+        self.enabled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::SeqCst);
+        *self.reason.write() = None;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    pub fn reason(&self) -> Option<String> {
+        self.reason.read().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle() {
+        let m = MaintenanceMode::new();
+        assert!(!m.is_enabled());
+        m.enable("db migration");
+        assert!(m.is_enabled());
+        assert_eq!(m.reason().as_deref(), Some("db migration"));
+        m.disable();
+        assert!(!m.is_enabled());
+        assert!(m.reason().is_none());
+    }
+}
@@ -1,3 +1,23 @@
+pub mod backfill;
 pub mod backup;
+pub mod balance_snapshots;
+pub mod block_tracking;
+pub mod bridge_poller;
+pub mod db_backup;
+pub mod deadman;
+pub mod envelope_rotation;
+pub mod fee_tracking;
 pub mod health;
+pub mod incidents;
+pub mod jobs;
+pub mod leases;
+pub mod maintenance;
 pub mod metrics;
+pub mod pool_stats;
+pub mod preflight;
+pub mod proof_of_reserves;
+pub mod reconciliation;
+pub mod sandbox_clone;
+pub mod secret_refresh;
+pub mod tx_expiry;
+pub mod wal;
@@ -0,0 +1,74 @@
+//! src/ops/pool_stats.rs
+//!
+//! Database pool gauges.
+//!
+//! Every few seconds the size, idle connections and longest acquire wait of
+//! each storage pool are copied into `database_pool_connections` and
+//! `database_pool_acquire_wait_seconds`. Each instance has its own pools, so
+//! the job runs everywhere and keeps running in maintenance mode.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
+
+use crate::core::config::DatabaseQueryConfig;
+use crate::monitoring::WalletMetrics;
+use crate::ops::jobs::{Job, Schedule};
+use crate::storage::{PoolStats, QueryTimeouts, WalletStorage};
+
+pub const POOL_STATS_JOB: &str = "pool_stats";
+
+impl From<&DatabaseQueryConfig> for QueryTimeouts {
+    fn from(config: &DatabaseQueryConfig) -> Self {
+        QueryTimeouts {
+            hot: Duration::from_millis(config.hot_timeout_ms),
+            standard: Duration::from_millis(config.standard_timeout_ms),
+            analytics: Duration::from_millis(config.analytics_timeout_ms),
+            slow: Duration::from_millis(config.slow_query_ms),
+        }
+    }
+}
+
+pub struct PoolStatsSampler {
+    storage: Arc<WalletStorage>,
+    metrics: Arc<WalletMetrics>,
+    interval: Duration,
+}
+
+impl PoolStatsSampler {
+    pub fn new(storage: Arc<WalletStorage>, metrics: Arc<WalletMetrics>, interval: Duration) -> Self {
+        Self { storage, metrics, interval }
+    }
+
+    /// Samples every pool into the gauges.
+    pub fn sample(&self) -> Vec<PoolStats> {
+        let stats = self.storage.pool_stats();
+        for pool in &stats {
+            self.metrics.set_database_pool_stats(pool.pool, pool.size, pool.idle, pool.max_acquire_wait.as_secs_f64());
+        }
+        stats
+    }
+}
+
+#[async_trait]
+impl Job for PoolStatsSampler {
+    fn name(&self) -> &str {
+        POOL_STATS_JOB
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: self.interval, immediate: true }
+    }
+
+    fn pauses_in_maintenance(&self) -> bool {
+        false
+    }
+
+    async fn run(&self, _cancel: CancellationToken) -> Result<()> {
+        self.sample();
+        Ok(())
+    }
+}
@@ -0,0 +1,437 @@
+//! src/ops/preflight.rs
+//!
+//! Startup dependency preflight. Every external dependency the server needs is
+//! probed once before it binds: database + schema, the master key, each
+//! configured network's RPC, writable directories and optional integrations.
+//! Failed required checks abort startup with [`PREFLIGHT_FAILURE_EXIT_CODE`];
+//! `hot_wallet server --check` runs only this phase and exits.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm,
+};
+use async_trait::async_trait;
+use base64::Engine as _;
+use ethers::providers::{Http, Middleware, Provider};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+use crate::core::config::{BackupSinkConfig, NetworkConfig, PreflightConfig, WalletConfig};
+use crate::security::env_manager::secret_backend;
+use crate::storage::WalletStorage;
+
+/// Process exit code when a required check fails
+pub const PREFLIGHT_FAILURE_EXIT_CODE: i32 = 2;
+
+/// Tables `WalletStorage` must have after schema initialisation
+const REQUIRED_TABLES: &[&str] =
+    &["wallets", "transactions", "audit_logs", "signing_intents", "events_journal"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    fn label(self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        }
+    }
+}
+
+/// Outcome of one probe
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    /// e.g. `database`, `master_key`, `rpc:eth`, `dir:./backups`, `integration:webhooks`
+    pub name: String,
+    /// A failed required check aborts startup; optional checks only warn
+    pub required: bool,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What the operator should change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    pub passed: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+impl PreflightReport {
+    fn new(checks: Vec<CheckResult>) -> Self {
+        let passed = !checks.iter().any(|c| c.required && c.status == CheckStatus::Fail);
+        Self { passed, checks }
+    }
+
+    pub fn check(&self, name: &str) -> Option<&CheckResult> {
+        self.checks.iter().find(|c| c.name == name)
+    }
+
+    /// Required checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.checks.iter().filter(|c| c.required && c.status == CheckStatus::Fail)
+    }
+
+    /// `0`, or [`PREFLIGHT_FAILURE_EXIT_CODE`] when a required check failed
+    pub fn exit_code(&self) -> i32 {
+        if self.passed {
+            0
+        } else {
+            PREFLIGHT_FAILURE_EXIT_CODE
+        }
+    }
+
+    /// Single-line JSON for supervisors that capture the log
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Human-readable table; hints follow the row they belong to.
+    pub fn to_table(&self) -> String {
+        let width = self.checks.iter().map(|c| c.name.len()).max().unwrap_or(0).max("CHECK".len());
+        let mut out = format!("{:<width$}  {:<6}  {:>7}  DETAIL\n", "CHECK", "STATUS", "MS");
+        for check in &self.checks {
+            out.push_str(&format!(
+                "{:<width$}  {:<6}  {:>7}  {}\n",
+                check.name,
+                check.status.label(),
+                check.elapsed_ms,
+                check.detail
+            ));
+            if let Some(hint) = &check.hint {
+                out.push_str(&format!("{:<width$}  {:<6}  {:>7}  -> {}\n", "", "", "", hint));
+            }
+        }
+        out.push_str(if self.passed { "preflight: OK\n" } else { "preflight: FAILED\n" });
+        out
+    }
+}
+
+/// Network access used by the probes; swapped out in tests.
+#[async_trait]
+pub trait ProbeTransport: Send + Sync {
+    /// `eth_chainId` of the node behind `rpc_url`
+    async fn chain_id(&self, rpc_url: &str) -> Result<u64, String>;
+    /// Liveness of an optional integration endpoint
+    async fn ping(&self, url: &str) -> Result<(), String>;
+}
+
+/// JSON-RPC via ethers, plain `GET` for integrations
+pub struct HttpProbeTransport {
+    client: reqwest::Client,
+}
+
+impl HttpProbeTransport {
+    pub fn new() -> Self {
+        Self { client: reqwest::Client::new() }
+    }
+}
+
+impl Default for HttpProbeTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ProbeTransport for HttpProbeTransport {
+    async fn chain_id(&self, rpc_url: &str) -> Result<u64, String> {
+        let provider = Provider::<Http>::try_from(rpc_url).map_err(|e| e.to_string())?;
+        provider.get_chainid().await.map(|id| id.as_u64()).map_err(|e| e.to_string())
+    }
+
+    async fn ping(&self, url: &str) -> Result<(), String> {
+        let response = self.client.get(url).send().await.map_err(|e| e.to_string())?;
+        if response.status().is_server_error() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+pub struct Preflight {
+    database_url: String,
+    networks: BTreeMap<String, NetworkConfig>,
+    master_key: Option<String>,
+    writable_dirs: Vec<PathBuf>,
+    integrations: BTreeMap<String, String>,
+    probe_timeout: Duration,
+    transport: Arc<dyn ProbeTransport>,
+}
+
+impl Preflight {
+    /// Checks derived from the wallet config; the master key is read the way
+    /// key storage reads it: from the installed secret backend, else
+    /// `WALLET_ENC_KEY`.
+    pub fn new(config: &WalletConfig, settings: &PreflightConfig) -> Self {
+        let mut writable_dirs = Vec::new();
+        if let Some(dir) = sqlite_parent_dir(&config.storage.database_url) {
+            writable_dirs.push(dir);
+        }
+        if config.backups.enabled {
+            if let BackupSinkConfig::Filesystem { directory } = &config.backups.sink {
+                writable_dirs.push(directory.clone());
+            }
+        }
+        writable_dirs.extend(settings.writable_dirs.iter().cloned());
+        writable_dirs.dedup();
+
+        Self {
+            database_url: config.storage.database_url.clone(),
+            networks: config.blockchain.networks.clone().into_iter().collect(),
+            master_key: secret_backend::master_key_encoded().ok().map(|key| key.to_string()),
+            writable_dirs,
+            integrations: settings.integrations.clone(),
+            probe_timeout: Duration::from_secs(settings.probe_timeout_secs.max(1)),
+            transport: Arc::new(HttpProbeTransport::new()),
+        }
+    }
+
+    pub fn with_master_key(mut self, master_key: Option<String>) -> Self {
+        self.master_key = master_key;
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn ProbeTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    pub fn with_probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = timeout;
+        self
+    }
+
+    pub async fn run(&self) -> PreflightReport {
+        let mut checks = vec![self.check_database().await, self.check_master_key()];
+
+        // Networks are probed concurrently so one slow node costs one timeout, not N.
+        let rpc = self.networks.iter().map(|(name, network)| self.check_rpc(name, network));
+        checks.extend(futures::future::join_all(rpc).await);
+
+        checks.extend(self.writable_dirs.iter().map(check_writable));
+
+        let integrations = self.integrations.iter().map(|(name, url)| self.check_integration(name, url));
+        checks.extend(futures::future::join_all(integrations).await);
+
+        PreflightReport::new(checks)
+    }
+
+    async fn check_database(&self) -> CheckResult {
+        let started = Instant::now();
+        let hint = "check DATABASE_URL and that the database file is readable and writable";
+        let (status, detail, hint) = match self.timed(WalletStorage::new_with_url(&self.database_url)).await {
+            Err(elapsed) => (CheckStatus::Fail, elapsed, Some(hint.to_string())),
+            Ok(Err(e)) => (CheckStatus::Fail, format!("connect failed: {}", e), Some(hint.to_string())),
+            Ok(Ok(storage)) => match storage.missing_tables(REQUIRED_TABLES).await {
+                Ok(missing) if missing.is_empty() => {
+                    (CheckStatus::Pass, format!("reachable, {} tables present", REQUIRED_TABLES.len()), None)
+                }
+                Ok(missing) => (
+                    CheckStatus::Fail,
+                    format!("schema out of date, missing: {}", missing.join(", ")),
+                    Some("the database was created by an older release; restore a current backup or run the server once with write access".to_string()),
+                ),
+                Err(e) => (CheckStatus::Fail, format!("schema query failed: {}", e), Some(hint.to_string())),
+            },
+        };
+        result("database", true, status, detail, hint, started)
+    }
+
+    fn check_master_key(&self) -> CheckResult {
+        let started = Instant::now();
+        let (status, detail, hint) = match master_key_probe(self.master_key.as_deref()) {
+            Ok(()) => (CheckStatus::Pass, "32-byte key, AES-256-GCM round-trip ok".to_string(), None),
+            Err((detail, hint)) => (CheckStatus::Fail, detail, Some(hint)),
+        };
+        result("master_key", true, status, detail, hint, started)
+    }
+
+    async fn check_rpc(&self, name: &str, network: &NetworkConfig) -> CheckResult {
+        let started = Instant::now();
+        let check = format!("rpc:{}", name);
+        if network.rpc_url.trim().is_empty() {
+            return result(
+                &check,
+                true,
+                CheckStatus::Fail,
+                "no rpc_url configured".to_string(),
+                Some(format!("set [blockchain.networks.{}].rpc_url in config.toml", name)),
+                started,
+            );
+        }
+        let (status, detail, hint) = match self.timed(self.transport.chain_id(&network.rpc_url)).await {
+            Err(elapsed) => (
+                CheckStatus::Fail,
+                elapsed,
+                Some(format!("RPC for {} is unreachable or too slow; check rpc_url and network egress", name)),
+            ),
+            Ok(Err(e)) => (
+                CheckStatus::Fail,
+                format!("eth_chainId failed: {}", e),
+                Some(format!("RPC for {} is unreachable; check rpc_url and network egress", name)),
+            ),
+            Ok(Ok(id)) if id == network.chain_id => (CheckStatus::Pass, format!("chain_id {}", id), None),
+            Ok(Ok(id)) => (
+                CheckStatus::Fail,
+                format!("chain_id {} but expected {}", id, network.chain_id),
+                Some(format!("rpc_url for {} points at a different chain; fix rpc_url or chain_id", name)),
+            ),
+        };
+        result(&check, true, status, detail, hint, started)
+    }
+
+    async fn check_integration(&self, name: &str, url: &str) -> CheckResult {
+        let started = Instant::now();
+        let (status, detail) = match self.timed(self.transport.ping(url)).await {
+            Ok(Ok(())) => (CheckStatus::Pass, "reachable".to_string()),
+            Ok(Err(e)) => (CheckStatus::Warn, format!("ping failed: {}", e)),
+            Err(elapsed) => (CheckStatus::Warn, elapsed),
+        };
+        let hint = (status != CheckStatus::Pass)
+            .then(|| format!("{} stays degraded until {} responds", name, url));
+        result(&format!("integration:{}", name), false, status, detail, hint, started)
+    }
+
+    async fn timed<T>(&self, fut: impl Future<Output = T>) -> Result<T, String> {
+        tokio::time::timeout(self.probe_timeout, fut)
+            .await
+            .map_err(|_| format!("timed out after {}s", self.probe_timeout.as_secs_f32()))
+    }
+}
+
+fn result(
+    name: &str,
+    required: bool,
+    status: CheckStatus,
+    detail: String,
+    hint: Option<String>,
+    started: Instant,
+) -> CheckResult {
+    CheckResult {
+        name: name.to_string(),
+        required,
+        status,
+        detail,
+        hint,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Decodes the base64 master key and encrypts/decrypts a probe with it.
+/// Errors are `(detail, hint)`.
+pub fn master_key_probe(encoded: Option<&str>) -> Result<(), (String, String)> {
+    let encoded = encoded.ok_or_else(|| {
+        ("WALLET_ENC_KEY is not set".to_string(), "export WALLET_ENC_KEY as a base64-encoded 32-byte key".to_string())
+    })?;
+    let key = Zeroizing::new(base64::engine::general_purpose::STANDARD.decode(encoded.trim()).map_err(|_| {
+        (
+            "WALLET_ENC_KEY is not valid base64".to_string(),
+            "generate one with `openssl rand -base64 32`".to_string(),
+        )
+    })?);
+    if key.len() != 32 {
+        return Err((
+            format!("WALLET_ENC_KEY decodes to {} bytes — expected 32", key.len()),
+            "generate one with `openssl rand -base64 32`".to_string(),
+        ));
+    }
+    #[cfg(not(any(test, feature = "test-env")))]
+    if key.iter().all(|&b| b == 0) {
+        return Err((
+            "WALLET_ENC_KEY is all zeros".to_string(),
+            "replace the placeholder key with `openssl rand -base64 32`".to_string(),
+        ));
+    }
+
+    let cipher = Aes256Gcm::new_from_slice(&key)
+        .map_err(|_| ("AES-256-GCM rejected the key".to_string(), "regenerate WALLET_ENC_KEY".to_string()))?;
+    let mut nonce_bytes = [1u8; 12];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    #[allow(deprecated)]
+    let nonce = aes_gcm::aead::Nonce::<Aes256Gcm>::from_slice(&nonce_bytes);
+    let probe = b"hot-wallet-preflight";
+    let round_trip = cipher
+        .encrypt(nonce, probe.as_ref())
+        .and_then(|ciphertext| cipher.decrypt(nonce, ciphertext.as_ref()));
+    match round_trip {
+        Ok(plain) if plain == probe => Ok(()),
+        _ => Err((
+            "encrypt/decrypt probe did not round-trip".to_string(),
+            "the crypto backend is misbehaving; check the build and CPU features".to_string(),
+        )),
+    }
+}
+
+fn check_writable(dir: &PathBuf) -> CheckResult {
+    let started = Instant::now();
+    let probe = dir.join(format!(".preflight-{}", uuid::Uuid::new_v4()));
+    let outcome = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe));
+    let (status, detail, hint) = match outcome {
+        Ok(()) => (CheckStatus::Pass, "writable".to_string(), None),
+        Err(e) => (
+            CheckStatus::Fail,
+            format!("not writable: {}", e),
+            Some(format!("grant the service user write access to {}", dir.display())),
+        ),
+    };
+    result(&format!("dir:{}", dir.display()), true, status, detail, hint, started)
+}
+
+/// Directory holding a file-backed sqlite database
+fn sqlite_parent_dir(database_url: &str) -> Option<PathBuf> {
+    let path = database_url
+        .strip_prefix("sqlite://")
+        .or_else(|| database_url.strip_prefix("sqlite:"))?;
+    let path = path.split('?').next().unwrap_or(path);
+    if path.is_empty() || path.contains(":memory:") {
+        return None;
+    }
+    match std::path::Path::new(path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => Some(parent.to_path_buf()),
+        _ => Some(PathBuf::from(".")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_master_key_length_hint() {
+        let short = base64::engine::general_purpose::STANDARD.encode([7u8; 31]);
+        let (detail, _) = master_key_probe(Some(&short)).unwrap_err();
+        assert_eq!(detail, "WALLET_ENC_KEY decodes to 31 bytes — expected 32");
+
+        assert!(master_key_probe(None).is_err());
+        assert!(master_key_probe(Some("not base64!")).is_err());
+        let good = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+        assert!(master_key_probe(Some(&good)).is_ok());
+    }
+
+    #[test]
+    fn test_sqlite_parent_dir() {
+        assert_eq!(sqlite_parent_dir("sqlite://./data/wallet.db?mode=rwc"), Some(PathBuf::from("./data")));
+        assert_eq!(sqlite_parent_dir("sqlite:wallets.db"), Some(PathBuf::from(".")));
+        assert_eq!(sqlite_parent_dir("sqlite::memory:"), None);
+        assert_eq!(sqlite_parent_dir("postgres://db"), None);
+    }
+}
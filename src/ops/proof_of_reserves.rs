@@ -0,0 +1,467 @@
+//! src/ops/proof_of_reserves.rs
+//!
+//! Proof-of-reserves reports: every managed wallet's balance per network and
+//! configured token, read at one block height per network, with per-asset
+//! totals, signed with EIP-191 over the report's canonical JSON.
+//!
+//! Canonical JSON is the report serialized with object keys sorted and no
+//! insignificant whitespace, so an auditor can re-serialize the `report`
+//! object of a document in any language and check the signature without
+//! trusting our bytes. Amounts are decimal strings in the asset's minimal
+//! unit (wei, token base units) so totals add up exactly.
+//!
+//! A balance that could not be read is kept as an `unverified` row with no
+//! amount and the reason; it is excluded from the totals, which count it
+//! separately, so a report never silently under-states what it covers.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use ethers::types::{Address, U256};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::blockchain::gas_oracle::GasOracle;
+use crate::core::errors::WalletError;
+use crate::crypto::message_signing::{eip191_recover, MessageScheme, MessageSigner};
+
+/// Format version of the report document
+pub const REPORT_VERSION: u32 = 1;
+
+/// `(network, asset, token)`
+type AssetKey<'a> = (&'a str, &'a str, Option<&'a str>);
+
+/// Upper bound on a single block-number or balance read
+const BALANCE_READ_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowStatus {
+    /// Balance read at the network's recorded block height
+    Verified,
+    /// Balance could not be read; `amount` is absent
+    Unverified,
+}
+
+/// One wallet's holding of one asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReserveRow {
+    pub wallet_name: String,
+    /// EIP-55
+    pub address: String,
+    pub network: String,
+    /// Native symbol or configured token symbol
+    pub asset: String,
+    /// Token contract (EIP-55); absent for the native coin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Minimal units; absent when unverified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    pub status: RowStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Sum over the verified rows of one asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetTotal {
+    pub network: String,
+    pub asset: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Minimal units
+    pub amount: String,
+    pub verified_rows: usize,
+    pub unverified_rows: usize,
+}
+
+/// The signed part of a proof-of-reserves document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReserveReport {
+    pub version: u32,
+    /// RFC 3339
+    pub generated_at: String,
+    /// Wallet group the report is limited to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Block height every balance on a network was read at; networks whose
+    /// height could not be fetched are absent and all their rows unverified
+    pub block_heights: BTreeMap<String, u64>,
+    pub rows: Vec<ReserveRow>,
+    pub totals: Vec<AssetTotal>,
+}
+
+/// A managed wallet covered by a report
+#[derive(Debug, Clone)]
+pub struct ReserveWallet {
+    pub name: String,
+    pub address: Address,
+}
+
+/// An asset read for every wallet
+#[derive(Debug, Clone)]
+pub struct ReserveAsset {
+    pub network: String,
+    pub symbol: String,
+    /// `None` for the native coin
+    pub token: Option<Address>,
+}
+
+/// Result of checking a presented document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportVerification {
+    /// Signature by the claimed signer and totals consistent with the rows
+    pub valid: bool,
+    pub signature_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovered_signer: Option<String>,
+    pub signer_matches: bool,
+    pub totals_match: bool,
+    /// `network:asset` of totals that differ from the recomputed sums
+    pub mismatched_totals: Vec<String>,
+    pub rows: usize,
+    pub unverified_rows: usize,
+    /// Why the report could not be interpreted at all
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ReserveReport {
+    /// Builds a report over `rows`, computing the totals.
+    pub fn new(
+        generated_at: String,
+        group: Option<String>,
+        block_heights: BTreeMap<String, u64>,
+        rows: Vec<ReserveRow>,
+    ) -> Result<Self, WalletError> {
+        let totals = compute_totals(&rows)?;
+        Ok(Self { version: REPORT_VERSION, generated_at, group, block_heights, rows, totals })
+    }
+
+    pub fn unverified_rows(&self) -> usize {
+        self.rows.iter().filter(|r| r.status == RowStatus::Unverified).count()
+    }
+
+    /// Canonical JSON: sorted keys, compact.
+    pub fn canonical_json(&self) -> Result<String, WalletError> {
+        let value = serde_json::to_value(self)
+            .map_err(|e| WalletError::SerializationError(format!("Failed to serialize report: {}", e)))?;
+        Ok(canonical_json(&value))
+    }
+
+    /// EIP-191 signature of the canonical JSON.
+    pub fn sign(&self, signer: &MessageSigner) -> Result<String, WalletError> {
+        if signer.scheme() != MessageScheme::Eip191 {
+            return Err(WalletError::ValidationError("Reserve reports are signed with an EVM key".to_string()));
+        }
+        signer.sign(self.canonical_json()?.as_bytes())
+    }
+}
+
+/// `value` serialized with object keys in byte order and no whitespace.
+///
+/// `serde_json::Map` is ordered by key unless the `preserve_order` feature is
+/// enabled, which this crate does not do.
+pub fn canonical_json(value: &serde_json::Value) -> String {
+    value.to_string()
+}
+
+/// Per-asset sums of the verified rows, ordered by network, asset and token.
+pub fn compute_totals(rows: &[ReserveRow]) -> Result<Vec<AssetTotal>, WalletError> {
+    let mut totals: BTreeMap<AssetKey, (U256, usize, usize)> = BTreeMap::new();
+    for row in rows {
+        let entry = totals.entry((&row.network, &row.asset, row.token.as_deref())).or_default();
+        match (row.status, &row.amount) {
+            (RowStatus::Verified, Some(amount)) => {
+                let amount = U256::from_dec_str(amount).map_err(|_| {
+                    WalletError::ValidationError(format!(
+                        "Row {} {} {} has a non-integer amount: {}",
+                        row.wallet_name, row.network, row.asset, amount
+                    ))
+                })?;
+                entry.0 = entry.0.checked_add(amount).ok_or_else(|| {
+                    WalletError::ValidationError(format!("Total of {} {} overflows", row.network, row.asset))
+                })?;
+                entry.1 += 1;
+            }
+            (RowStatus::Verified, None) => {
+                return Err(WalletError::ValidationError(format!(
+                    "Verified row {} {} {} has no amount",
+                    row.wallet_name, row.network, row.asset
+                )))
+            }
+            (RowStatus::Unverified, _) => entry.2 += 1,
+        }
+    }
+    Ok(totals
+        .into_iter()
+        .map(|((network, asset, token), (amount, verified_rows, unverified_rows))| AssetTotal {
+            network: network.to_string(),
+            asset: asset.to_string(),
+            token: token.map(str::to_string),
+            amount: amount.to_string(),
+            verified_rows,
+            unverified_rows,
+        })
+        .collect())
+}
+
+/// Reads every wallet's balance of every asset, at most `concurrency` at a
+/// time, pinned to one block height per network. Rows are ordered by wallet,
+/// network and asset.
+pub async fn collect_reserves(
+    oracle: &dyn GasOracle,
+    wallets: &[ReserveWallet],
+    assets: &[ReserveAsset],
+    concurrency: usize,
+) -> (BTreeMap<String, u64>, Vec<ReserveRow>) {
+    let mut networks: Vec<&str> = assets.iter().map(|a| a.network.as_str()).collect();
+    networks.sort_unstable();
+    networks.dedup();
+
+    let mut block_heights = BTreeMap::new();
+    let mut height_errors = BTreeMap::new();
+    for network in networks {
+        match with_timeout(oracle.block_number(network)).await {
+            Ok(height) => {
+                block_heights.insert(network.to_string(), height);
+            }
+            Err(e) => {
+                warn!("proof of reserves: no block height for {}: {}", network, e);
+                height_errors.insert(network, e.to_string());
+            }
+        }
+    }
+
+    // Owned jobs: the buffered futures must not hold borrows of the inputs, or
+    // the report handler's future is not `Send`
+    let jobs: Vec<(ReserveWallet, ReserveAsset)> = wallets
+        .iter()
+        .flat_map(|wallet| assets.iter().map(move |asset| (wallet.clone(), asset.clone())))
+        .collect();
+    let mut rows: Vec<ReserveRow> = stream::iter(jobs)
+        .map(|(wallet, asset)| {
+            let height = block_heights.get(&asset.network).copied();
+            let height_error = height_errors.get(asset.network.as_str()).cloned();
+            async move {
+                let result = match (height, height_error) {
+                    (Some(block), _) => {
+                        let read = match asset.token {
+                            None => with_timeout(oracle.native_balance_at(&asset.network, wallet.address, block)).await,
+                            Some(token) => {
+                                with_timeout(oracle.token_balance_at(&asset.network, token, wallet.address, block))
+                                    .await
+                            }
+                        };
+                        read.map_err(|e| e.to_string())
+                    }
+                    (None, error) => Err(format!(
+                        "Block height unavailable: {}",
+                        error.as_deref().unwrap_or("network not queried")
+                    )),
+                };
+                reserve_row(&wallet, &asset, result)
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+    rows.sort_by(|a, b| {
+        (&a.wallet_name, &a.address, &a.network, &a.asset).cmp(&(&b.wallet_name, &b.address, &b.network, &b.asset))
+    });
+    (block_heights, rows)
+}
+
+async fn with_timeout<T>(read: impl std::future::Future<Output = Result<T, WalletError>>) -> Result<T, WalletError> {
+    tokio::time::timeout(BALANCE_READ_TIMEOUT, read)
+        .await
+        .map_err(|_| WalletError::NetworkError("RPC read timed out".to_string()))?
+}
+
+fn reserve_row(wallet: &ReserveWallet, asset: &ReserveAsset, result: Result<U256, String>) -> ReserveRow {
+    let (amount, status, error) = match result {
+        Ok(amount) => (Some(amount.to_string()), RowStatus::Verified, None),
+        Err(e) => {
+            warn!("proof of reserves: {} {} balance of {} unverified: {}", asset.network, asset.symbol, wallet.name, e);
+            (None, RowStatus::Unverified, Some(e))
+        }
+    };
+    ReserveRow {
+        wallet_name: wallet.name.clone(),
+        address: ethers::utils::to_checksum(&wallet.address, None),
+        network: asset.network.clone(),
+        asset: asset.symbol.clone(),
+        token: asset.token.map(|t| ethers::utils::to_checksum(&t, None)),
+        amount,
+        status,
+        error,
+    }
+}
+
+/// Checks a presented document without any stored state: the EIP-191
+/// signature over the canonical JSON of `report` must recover to `signer`,
+/// and the stated totals must equal the sums recomputed from the rows.
+pub fn verify_report(report: &serde_json::Value, signer: &str, signature: &str) -> ReportVerification {
+    let recovered = eip191_recover(canonical_json(report).as_bytes(), signature)
+        .ok()
+        .map(|address| ethers::utils::to_checksum(&address, None));
+    let signer_matches = recovered.as_deref().is_some_and(|r| r.eq_ignore_ascii_case(signer.trim()));
+    let mut verification = ReportVerification {
+        valid: false,
+        signature_valid: recovered.is_some(),
+        recovered_signer: recovered,
+        signer_matches,
+        totals_match: false,
+        mismatched_totals: Vec::new(),
+        rows: 0,
+        unverified_rows: 0,
+        error: None,
+    };
+
+    let parsed = serde_json::from_value::<ReserveReport>(report.clone())
+        .map_err(|e| WalletError::ValidationError(format!("Malformed report: {}", e)))
+        .and_then(|report| compute_totals(&report.rows).map(|totals| (report, totals)));
+    let (report, recomputed) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            verification.error = Some(e.to_string());
+            return verification;
+        }
+    };
+    verification.rows = report.rows.len();
+    verification.unverified_rows = report.unverified_rows();
+
+    let key = |t: &AssetTotal| format!("{}:{}", t.network, t.asset);
+    let stated: BTreeMap<String, &AssetTotal> = report.totals.iter().map(|t| (key(t), t)).collect();
+    let expected: BTreeMap<String, &AssetTotal> = recomputed.iter().map(|t| (key(t), t)).collect();
+    verification.mismatched_totals = stated
+        .keys()
+        .chain(expected.keys())
+        .filter(|k| stated.get(*k) != expected.get(*k))
+        .cloned()
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    verification.totals_match = verification.mismatched_totals.is_empty() && stated.len() == report.totals.len();
+    verification.valid = verification.signer_matches && verification.totals_match;
+    verification
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+
+    const KEY: [u8; 32] = [0x42; 32];
+
+    /// Native balances per (network, address); `polygon` has no block height.
+    struct FixedChain {
+        balances: HashMap<(String, Address), u64>,
+    }
+
+    #[async_trait]
+    impl GasOracle for FixedChain {
+        async fn gas_price(&self, _network: &str) -> Result<U256, WalletError> {
+            Ok(U256::zero())
+        }
+
+        async fn native_balance(&self, _network: &str, _address: &str) -> Result<U256, WalletError> {
+            Ok(U256::zero())
+        }
+
+        async fn block_number(&self, network: &str) -> Result<u64, WalletError> {
+            match network {
+                "polygon" => Err(WalletError::NetworkError("rpc down".to_string())),
+                _ => Ok(19_000_000),
+            }
+        }
+
+        async fn native_balance_at(&self, network: &str, address: Address, block: u64) -> Result<U256, WalletError> {
+            assert_eq!(block, 19_000_000);
+            self.balances
+                .get(&(network.to_string(), address))
+                .map(|b| U256::from(*b))
+                .ok_or_else(|| WalletError::NetworkError("no balance".to_string()))
+        }
+
+        fn supports(&self, _network: &str) -> bool {
+            true
+        }
+    }
+
+    fn native(network: &str, symbol: &str) -> ReserveAsset {
+        ReserveAsset { network: network.to_string(), symbol: symbol.to_string(), token: None }
+    }
+
+    async fn sample_report() -> ReserveReport {
+        let (a, b) = (Address::repeat_byte(0xaa), Address::repeat_byte(0xbb));
+        let balances = HashMap::from([(("eth".to_string(), a), 700), (("eth".to_string(), b), 300)]);
+        let wallets = [
+            ReserveWallet { name: "cold".to_string(), address: a },
+            ReserveWallet { name: "hot".to_string(), address: b },
+        ];
+        let assets = [native("eth", "ETH"), native("polygon", "MATIC")];
+        let (heights, rows) = collect_reserves(&FixedChain { balances }, &wallets, &assets, 4).await;
+        ReserveReport::new("2026-01-01T00:00:00+00:00".to_string(), None, heights, rows).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_collect_marks_failed_network_unverified() {
+        let report = sample_report().await;
+        assert_eq!(report.block_heights, BTreeMap::from([("eth".to_string(), 19_000_000)]));
+        assert_eq!(report.rows.len(), 4);
+        assert_eq!(report.unverified_rows(), 2);
+        assert!(report.rows.iter().filter(|r| r.network == "polygon").all(|r| r.amount.is_none()));
+
+        let eth = report.totals.iter().find(|t| t.network == "eth").unwrap();
+        assert_eq!((eth.amount.as_str(), eth.verified_rows, eth.unverified_rows), ("1000", 2, 0));
+        let polygon = report.totals.iter().find(|t| t.network == "polygon").unwrap();
+        assert_eq!((polygon.amount.as_str(), polygon.verified_rows, polygon.unverified_rows), ("0", 0, 2));
+    }
+
+    #[tokio::test]
+    async fn test_signature_round_trip_and_tampering() {
+        let report = sample_report().await;
+        let signer = MessageSigner::new(MessageScheme::Eip191, &KEY).unwrap();
+        let signature = report.sign(&signer).unwrap();
+        let document = serde_json::to_value(&report).unwrap();
+
+        let verification = verify_report(&document, signer.address(), &signature);
+        assert!(verification.valid, "{:?}", verification);
+        assert_eq!((verification.rows, verification.unverified_rows), (4, 2));
+
+        // Key order of the presented JSON does not matter
+        let reordered: serde_json::Value = serde_json::from_str(&report.canonical_json().unwrap()).unwrap();
+        assert!(verify_report(&reordered, &signer.address().to_lowercase(), &signature).valid);
+
+        // Editing a row breaks the signature and the totals
+        let mut tampered = document.clone();
+        tampered["rows"][0]["amount"] = serde_json::json!("7000");
+        let verification = verify_report(&tampered, signer.address(), &signature);
+        assert!(!verification.signer_matches);
+        assert!(!verification.totals_match);
+        assert_eq!(verification.mismatched_totals, vec!["eth:ETH".to_string()]);
+        assert!(!verification.valid);
+    }
+
+    #[test]
+    fn test_compute_totals_rejects_bad_amounts() {
+        let row = ReserveRow {
+            wallet_name: "w".to_string(),
+            address: "0x".to_string(),
+            network: "eth".to_string(),
+            asset: "ETH".to_string(),
+            token: None,
+            amount: Some("1.5".to_string()),
+            status: RowStatus::Verified,
+            error: None,
+        };
+        assert!(compute_totals(std::slice::from_ref(&row)).is_err());
+        assert!(compute_totals(&[ReserveRow { amount: None, ..row.clone() }]).is_err());
+        let unverified = ReserveRow { amount: None, status: RowStatus::Unverified, ..row };
+        assert_eq!(compute_totals(&[unverified]).unwrap()[0].unverified_rows, 1);
+    }
+}
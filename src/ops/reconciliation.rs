@@ -0,0 +1,157 @@
+//! Background runner for on-chain reconciliation requests.
+//!
+//! `POST /api/admin/reconciliations` queues a run and triggers this job; the
+//! job claims queued runs one at a time, runs
+//! [`WalletManager::reconcile_onchain`] with the backfill's history sources
+//! and stores the report. Claiming is atomic, so any instance may pick a run
+//! up and no lease is needed. A run interrupted by shutdown is marked failed
+//! rather than resumed: its corrections are already applied and the next
+//! request simply reports what is left.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::blockchain::history::{HistoryRpc, HistorySource};
+use crate::core::wallet_manager::reconcile::{
+    ReconcileContext, ReconcileError, ReconcileMode, ReconcileProgress, ReconcileRange, ReconciliationReport,
+};
+use crate::core::wallet_manager::WalletManager;
+use crate::ops::backfill::HistoryBackfill;
+use crate::ops::jobs::{Job, JobProgress, Schedule};
+use crate::security::error_sanitizer::sanitize_error_message;
+use crate::storage::{ReconciliationRun, WalletStorage};
+
+/// Job name; also `POST /api/admin/jobs/onchain_reconciliation/run`
+pub const RECONCILIATION_JOB: &str = "onchain_reconciliation";
+
+/// Runs queued before a trigger (or queued by another instance) wait at most this long
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+pub struct ReconciliationJob {
+    wallet_manager: Arc<WalletManager>,
+    storage: Arc<WalletStorage>,
+    backfill: Arc<HistoryBackfill>,
+    rpc: Arc<dyn HistoryRpc>,
+    /// Run id and progress of the run in progress
+    progress: Mutex<Option<(String, JobProgress)>>,
+}
+
+impl ReconciliationJob {
+    pub fn new(
+        wallet_manager: Arc<WalletManager>,
+        storage: Arc<WalletStorage>,
+        backfill: Arc<HistoryBackfill>,
+        rpc: Arc<dyn HistoryRpc>,
+    ) -> Self {
+        Self { wallet_manager, storage, backfill, rpc, progress: Mutex::new(None) }
+    }
+
+    /// Progress of run `id`, while this instance is running it
+    pub fn progress_of(&self, id: &str) -> Option<JobProgress> {
+        self.progress.lock().as_ref().filter(|(run, _)| run == id).map(|(_, progress)| progress.clone())
+    }
+
+    /// Reconciles `range` in the foreground, outside any queued run (the CLI).
+    pub async fn run_direct(
+        &self,
+        range: ReconcileRange,
+        networks: &[String],
+        mode: ReconcileMode,
+        requested_by: &str,
+        cancel: &CancellationToken,
+    ) -> Result<ReconciliationReport, ReconcileError> {
+        let history = |network: &str| -> Arc<dyn HistorySource> { self.backfill.source(network).clone() };
+        let ctx = ReconcileContext {
+            storage: &self.storage,
+            rpc: self.rpc.as_ref(),
+            history: &history,
+            progress: None,
+            cancel,
+            requested_by,
+        };
+        self.wallet_manager.reconcile_onchain(&ctx, range, networks, mode).await
+    }
+
+    async fn execute(
+        &self,
+        run: &ReconciliationRun,
+        cancel: &CancellationToken,
+    ) -> Result<ReconciliationReport, ReconcileError> {
+        let history = |network: &str| -> Arc<dyn HistorySource> { self.backfill.source(network).clone() };
+        let progress = |p: &ReconcileProgress| {
+            let stage = format!("{} {}", p.network, p.stage);
+            let progress = JobProgress { stage, done: p.done, total: Some(p.total) };
+            *self.progress.lock() = Some((run.id.clone(), progress));
+        };
+        let ctx = ReconcileContext {
+            storage: &self.storage,
+            rpc: self.rpc.as_ref(),
+            history: &history,
+            progress: Some(&progress),
+            cancel,
+            requested_by: &run.requested_by,
+        };
+        let range = ReconcileRange { from: run.range_from, to: run.range_to };
+        let mode = if run.apply { ReconcileMode::Apply } else { ReconcileMode::Report };
+        self.wallet_manager.reconcile_onchain(&ctx, range, &run.networks, mode).await
+    }
+
+    /// Runs every queued run, oldest first; returns how many completed.
+    /// Fails with the last error when a run failed.
+    pub async fn run_queued(&self, cancel: &CancellationToken) -> anyhow::Result<usize> {
+        let mut completed = 0;
+        let mut last_error = None;
+        while !cancel.is_cancelled() {
+            let Some(run) = self.storage.claim_reconciliation().await? else { break };
+            *self.progress.lock() = Some((run.id.clone(), JobProgress::default()));
+            let outcome = self.execute(&run, cancel).await;
+            *self.progress.lock() = None;
+            match outcome {
+                Ok(report) => {
+                    self.storage.complete_reconciliation(&run.id, &report.totals, &report.discrepancies).await?;
+                    info!(
+                        "reconciliation {} completed: {} discrepancies, {} corrected",
+                        run.id, report.totals.discrepancies, report.totals.applied
+                    );
+                    completed += 1;
+                }
+                Err(e) => {
+                    let message = sanitize_error_message(&e.to_string());
+                    warn!("reconciliation {} failed: {}", run.id, message);
+                    self.storage.fail_reconciliation(&run.id, &message).await?;
+                    if !matches!(e, ReconcileError::Cancelled) {
+                        last_error = Some(message);
+                    }
+                }
+            }
+        }
+        match last_error {
+            Some(message) => anyhow::bail!("reconciliation failed: {}", message),
+            None => Ok(completed),
+        }
+    }
+}
+
+#[async_trait]
+impl Job for ReconciliationJob {
+    fn name(&self) -> &str {
+        RECONCILIATION_JOB
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: POLL_INTERVAL, immediate: true }
+    }
+
+    fn progress(&self) -> Option<JobProgress> {
+        self.progress.lock().as_ref().map(|(_, progress)| progress.clone())
+    }
+
+    async fn run(&self, cancel: CancellationToken) -> anyhow::Result<()> {
+        self.run_queued(&cancel).await.map(|_| ())
+    }
+}
@@ -0,0 +1,153 @@
+//! Cloning wallets into a sandbox database for staging tests.
+//!
+//! The clone keeps names, records and settings but none of the keys: every
+//! selected wallet is created afresh in the target under the sandbox
+//! password, and each address the source wallet used is replaced by the new
+//! wallet's address on the same network wherever it is stored. Tables that
+//! hold key material or secrets derived from the source keys
+//! ([`SKIPPED_TABLES`]) are not copied at all.
+//!
+//! Every wallet is created first and the copy starts only once the whole
+//! address map is known, so a transfer between two cloned wallets is
+//! rewritten on both ends. The target is marked as a sandbox before the
+//! first wallet is created. A second run against the same target reuses the
+//! sandbox wallets and their addresses and overwrites the copied rows.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::blockchain::client_registry::ClientRegistry;
+use crate::core::errors::WalletError;
+use crate::core::wallet_manager::{CreateWalletOptions, WalletManager};
+use crate::storage::{AddressRewrite, AddressRewrites, CloneSource, SourceWallet, WalletStorage, SKIPPED_TABLES};
+
+/// Outcome for one wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClonedWallet {
+    pub name: String,
+    pub source_id: String,
+    pub sandbox_id: String,
+    /// False when an earlier run had created the sandbox wallet already
+    pub created: bool,
+    /// Source networks the sandbox wallet has no address on; their addresses are left as they were
+    pub unmapped_networks: Vec<String>,
+    /// Rows written per table
+    pub rows: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CloneReport {
+    pub wallets: Vec<ClonedWallet>,
+    /// Every address rewrite applied, ordered by network and source address
+    pub addresses: Vec<AddressRewrite>,
+    pub skipped_tables: Vec<String>,
+}
+
+pub struct SandboxClone {
+    password: String,
+    wallets: Option<Vec<String>>,
+}
+
+impl SandboxClone {
+    /// `password` protects every sandbox wallet created by the run.
+    pub fn new(password: impl Into<String>) -> Self {
+        Self { password: password.into(), wallets: None }
+    }
+
+    /// Clones only the named wallets instead of every wallet in the source.
+    pub fn with_wallets(mut self, names: Vec<String>) -> Self {
+        self.wallets = Some(names);
+        self
+    }
+
+    pub async fn run(
+        &self,
+        source: &CloneSource,
+        target: &WalletStorage,
+        manager: &WalletManager,
+        clients: &ClientRegistry,
+    ) -> Result<CloneReport, WalletError> {
+        let storage_error = |e: anyhow::Error| WalletError::StorageError(e.to_string());
+        let wallets = self.select(source.wallets().await.map_err(storage_error)?)?;
+
+        let existing = target.list_wallets().await.map_err(storage_error)?;
+        if !existing.is_empty() && !target.is_sandbox().await.map_err(storage_error)? {
+            return Err(WalletError::ValidationError(
+                "target database holds wallets and is not a sandbox; refusing to clone into it".into(),
+            ));
+        }
+        target.mark_sandbox().await.map_err(storage_error)?;
+
+        let mut rewrites = AddressRewrites::default();
+        let mut cloned = Vec::with_capacity(wallets.len());
+        for wallet in &wallets {
+            let source_addresses = source.addresses(&wallet.name).await.map_err(storage_error)?;
+            let created = !existing.iter().any(|w| w.name == wallet.name);
+            if created {
+                let options = CreateWalletOptions {
+                    password: self.password.clone(),
+                    quantum_safe: wallet.quantum_safe,
+                    networks: source_addresses.iter().map(|(network, _)| network.clone()).collect(),
+                    derivation_path: None,
+                    passphrase: None,
+                };
+                manager.create_wallet_full(&wallet.name, options, target, clients).await?;
+            }
+            let sandbox_addresses = target.wallet_networks(&wallet.name).await.map_err(storage_error)?;
+            let mut unmapped_networks = Vec::new();
+            for (network, address) in &source_addresses {
+                match sandbox_addresses.iter().find(|n| n.network == *network) {
+                    Some(sandbox) => rewrites.insert(network, address, &sandbox.address),
+                    None => unmapped_networks.push(network.clone()),
+                }
+            }
+            if !unmapped_networks.is_empty() {
+                warn!("sandbox wallet {} has no address on {:?}; left unrewritten", wallet.name, unmapped_networks);
+            }
+            let sandbox_id = target
+                .list_wallets()
+                .await
+                .map_err(storage_error)?
+                .into_iter()
+                .find(|w| w.name == wallet.name)
+                .map(|w| w.id)
+                .ok_or_else(|| WalletError::NotFoundError(format!("sandbox wallet '{}' not found", wallet.name)))?;
+            cloned.push(ClonedWallet {
+                name: wallet.name.clone(),
+                source_id: wallet.id.clone(),
+                sandbox_id,
+                created,
+                unmapped_networks,
+                rows: BTreeMap::new(),
+            });
+        }
+
+        for (wallet, report) in wallets.iter().zip(cloned.iter_mut()) {
+            let rows = target
+                .import_sandbox_wallet(source, wallet, &report.sandbox_id, &rewrites)
+                .await
+                .map_err(storage_error)?;
+            report.rows = rows.into_iter().map(|(table, n)| (table.to_string(), n)).collect();
+            info!(wallet = %wallet.name, created = report.created, "cloned wallet into sandbox");
+        }
+
+        Ok(CloneReport {
+            wallets: cloned,
+            addresses: rewrites.entries(),
+            skipped_tables: SKIPPED_TABLES.iter().map(|t| t.to_string()).collect(),
+        })
+    }
+
+    /// The requested wallets in name order; every requested name must exist in the source.
+    fn select(&self, available: Vec<SourceWallet>) -> Result<Vec<SourceWallet>, WalletError> {
+        let Some(names) = &self.wallets else {
+            return Ok(available);
+        };
+        if let Some(missing) = names.iter().find(|name| !available.iter().any(|w| w.name == **name)) {
+            return Err(WalletError::NotFoundError(format!("wallet '{}' not found in the source database", missing)));
+        }
+        Ok(available.into_iter().filter(|w| names.contains(&w.name)).collect())
+    }
+}
@@ -0,0 +1,184 @@
+//! Periodic re-read of the secret backend.
+//!
+//! Each run asks the installed [`SecretStore`] to fetch every secret again.
+//! Unchanged values are re-confirmed, which keeps them inside
+//! `max_staleness_secs`; a failed read leaves the cache alone so the keys
+//! expire on schedule while the backend is unreachable.
+//!
+//! A different master key cannot simply be swapped in: every wallet envelope
+//! is sealed under the old one. With [`MasterKeyRotation::Refuse`] the store
+//! stops handing out the master key until an operator has run `rotate-kek`
+//! and restarted. With [`MasterKeyRotation::Rewrap`] the envelopes are
+//! re-wrapped under the new key first and the key is adopted only once that
+//! committed, or once another instance is found to have done it already.
+//!
+//! Admin API keys carry no such state, so after every successful read the
+//! server's [`ApiKeyRing`] takes whatever the backend holds. A rotation on one
+//! instance is saved to the backend and reaches the others on their next run.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use crate::api::middleware::ApiKeyRing;
+use crate::core::config::MasterKeyRotation;
+use crate::ops::envelope_rotation::{decode_kek, EnvelopeRotation};
+use crate::ops::jobs::{Job, Schedule};
+use crate::security::env_manager::secret_backend::SecretStore;
+use crate::security::secret::SecretVec;
+use crate::storage::WalletStorage;
+
+/// Scheduler job name
+pub const SECRET_REFRESH_JOB: &str = "secret_refresh";
+
+/// Result of one refresh
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefreshOutcome {
+    /// Every secret read back and the master key did not change
+    Unchanged,
+    /// The backend could not be read; cached keys age towards staleness
+    Failed(String),
+    /// The envelopes were re-wrapped under the new master key, which is now in use
+    Rewrapped { kek_id: String, wallets: usize },
+    /// Another instance had re-wrapped the envelopes already; the new key is in use
+    AlreadyRewrapped,
+    /// The new master key was not adopted; the master key is unavailable
+    Refused(String),
+}
+
+pub struct SecretRefresher {
+    store: Arc<SecretStore>,
+    storage: Arc<WalletStorage>,
+    interval: Duration,
+    on_rotation: MasterKeyRotation,
+    api_keys: Option<ApiKeyRing>,
+}
+
+impl SecretRefresher {
+    pub fn new(
+        store: Arc<SecretStore>,
+        storage: Arc<WalletStorage>,
+        interval: Duration,
+        on_rotation: MasterKeyRotation,
+    ) -> Self {
+        Self { store, storage, interval, on_rotation, api_keys: None }
+    }
+
+    /// Keep `ring` in step with the backend's `API_KEY` / `API_KEY_SECONDARY`.
+    pub fn with_api_keys(mut self, ring: ApiKeyRing) -> Self {
+        self.api_keys = Some(ring);
+        self
+    }
+
+    pub async fn run_once(&self) -> RefreshOutcome {
+        let new_key = match self.store.refresh().await {
+            Ok(new_key) => new_key,
+            Err(e) => {
+                warn!("secret refresh from the {} backend failed: {}", self.store.backend_kind(), e);
+                return RefreshOutcome::Failed(e.to_string());
+            }
+        };
+        self.sync_api_keys();
+        let Some(new_key) = new_key else { return RefreshOutcome::Unchanged };
+        warn!("the master key changed in the {} secret backend", self.store.backend_kind());
+        let outcome = match self.on_rotation {
+            MasterKeyRotation::Refuse => RefreshOutcome::Refused(
+                "automatic re-wrap is disabled; run `rotate-kek` with the old and new keys, then restart".to_string(),
+            ),
+            MasterKeyRotation::Rewrap => self.rewrap(&new_key).await,
+        };
+        match &outcome {
+            RefreshOutcome::Refused(reason) => {
+                error!("master key rotation refused: {}", reason);
+                self.store.refuse_master_key(reason.clone());
+            }
+            _ => self.store.adopt_master_key(new_key),
+        }
+        outcome
+    }
+
+    /// A backend without `API_KEY` leaves the ring alone: removing the key
+    /// there must not switch authentication off.
+    fn sync_api_keys(&self) {
+        let Some(ring) = &self.api_keys else { return };
+        match self.store.api_keys() {
+            Ok(Some(keys)) if !ring.holds(&keys) => {
+                info!("admin API keys changed in the {} secret backend; now in use", self.store.backend_kind());
+                ring.replace(keys);
+            }
+            Ok(Some(_)) => {}
+            Ok(None) if !ring.is_none() => {
+                warn!("API_KEY is gone from the {} secret backend; keeping the current keys", self.store.backend_kind())
+            }
+            Ok(None) => {}
+            Err(e) => warn!("admin API keys not refreshed: {}", e),
+        }
+    }
+
+    async fn rewrap(&self, new_key: &SecretVec) -> RefreshOutcome {
+        let decode = |key: &SecretVec| {
+            std::str::from_utf8(key)
+                .map_err(|_| "not UTF-8".to_string())
+                .and_then(|encoded| decode_kek(encoded).map_err(|e| e.to_string()))
+        };
+        let Some(old_key) = self.store.cached_master_key() else {
+            return RefreshOutcome::Refused("no previous master key to unwrap with".to_string());
+        };
+        let (old_kek, new_kek) = match (decode(&old_key), decode(new_key)) {
+            (Ok(old_kek), Ok(new_kek)) => (old_kek, new_kek),
+            (Err(e), _) | (_, Err(e)) => return RefreshOutcome::Refused(format!("invalid master key: {}", e)),
+        };
+
+        let report = match EnvelopeRotation::new(old_kek, new_kek.clone()).run(&self.storage, false).await {
+            Ok(report) => report,
+            Err(e) => return RefreshOutcome::Refused(format!("re-wrap failed: {}", e)),
+        };
+        if report.applied {
+            info!(kek_id = %report.kek_id, wallets = report.totals.wallets, "re-wrapped wallet envelopes");
+            return RefreshOutcome::Rewrapped { kek_id: report.kek_id, wallets: report.totals.wallets };
+        }
+
+        // Nothing opened with the old key: another instance sharing the
+        // database may have re-wrapped already. Adopt only if every envelope
+        // opens with the new key.
+        match EnvelopeRotation::new(new_kek.clone(), new_kek).run(&self.storage, true).await {
+            Ok(check) if check.totals.failed == 0 => RefreshOutcome::AlreadyRewrapped,
+            Ok(_) => RefreshOutcome::Refused(format!(
+                "{} of {} wallets could not be re-wrapped; nothing was written",
+                report.totals.failed, report.totals.wallets
+            )),
+            Err(e) => RefreshOutcome::Refused(format!("re-wrap check failed: {}", e)),
+        }
+    }
+}
+
+#[async_trait]
+impl Job for SecretRefresher {
+    fn name(&self) -> &str {
+        SECRET_REFRESH_JOB
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: self.interval, immediate: false }
+    }
+
+    /// Keys must keep being confirmed during maintenance too.
+    fn pauses_in_maintenance(&self) -> bool {
+        false
+    }
+
+    async fn run(&self, _cancel: CancellationToken) -> Result<()> {
+        match self.run_once().await {
+            RefreshOutcome::Failed(reason) => anyhow::bail!("secret backend unavailable: {}", reason),
+            RefreshOutcome::Refused(reason) => anyhow::bail!("master key rotation refused: {}", reason),
+            outcome => {
+                debug!("secret refresh: {:?}", outcome);
+                Ok(())
+            }
+        }
+    }
+}
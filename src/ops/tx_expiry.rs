@@ -0,0 +1,147 @@
+//! src/ops/tx_expiry.rs
+//!
+//! Gives up on sends stuck in `pending`.
+//!
+//! Networks with `pending_expiry_seconds` set get a deadline: a transaction
+//! still pending past it is checked once more on chain, and only when there
+//! is no receipt *and* the node no longer knows the hash (dropped from the
+//! mempool) is it moved to the terminal `expired` status. Its signing intent
+//! is then released so the nonce can be reused, and since duplicate detection
+//! only matches pending sends, the same transfer may be submitted again.
+//!
+//! Expiry is a judgement, not an on-chain fact: a dropped transaction can be
+//! rebroadcast by anyone holding the raw bytes. If one is mined later, the
+//! confirmation poller or an admin recheck moves it to `confirmed` and the
+//! storage layer books a ledger correction.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use ethers::types::H256;
+use tracing::{info, warn};
+
+use crate::core::config::BlockchainConfig;
+use crate::intents::BroadcastChain;
+use crate::monitoring::WalletMetrics;
+use crate::storage::{TransactionRecord, WalletStorage, INTENT_RECORDED};
+
+/// Stale transactions looked at per network and pass
+const EXPIRY_BATCH: i64 = 100;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpiryReport {
+    /// Past their deadline
+    pub checked: usize,
+    pub expired: usize,
+    /// Still in the mempool or already mined; left for the confirmation poller
+    pub still_known: usize,
+    /// Expired transactions whose nonce went back to the sender
+    pub nonces_released: usize,
+    pub errors: usize,
+}
+
+pub struct PendingExpiry {
+    storage: Arc<WalletStorage>,
+    chain: Arc<dyn BroadcastChain>,
+    deadlines: BTreeMap<String, Duration>,
+    metrics: Option<Arc<WalletMetrics>>,
+}
+
+impl PendingExpiry {
+    pub fn new(storage: Arc<WalletStorage>, chain: Arc<dyn BroadcastChain>) -> Self {
+        Self { storage, chain, deadlines: BTreeMap::new(), metrics: None }
+    }
+
+    /// Deadlines of every network with `pending_expiry_seconds` configured.
+    pub fn from_config(config: &BlockchainConfig, storage: Arc<WalletStorage>, chain: Arc<dyn BroadcastChain>) -> Self {
+        config
+            .networks
+            .iter()
+            .filter_map(|(name, network)| Some((name, network.pending_expiry_seconds?)))
+            .fold(Self::new(storage, chain), |expiry, (name, secs)| {
+                expiry.with_deadline(name, Duration::from_secs(secs))
+            })
+    }
+
+    pub fn with_deadline(mut self, network: &str, deadline: Duration) -> Self {
+        self.deadlines.insert(network.to_string(), deadline);
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<WalletMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// No network has a deadline
+    pub fn is_empty(&self) -> bool {
+        self.deadlines.is_empty()
+    }
+
+    /// One pass over every network with a deadline.
+    pub async fn run_once(&self) -> ExpiryReport {
+        let mut report = ExpiryReport::default();
+        for (network, deadline) in &self.deadlines {
+            let cutoff = Utc::now() - chrono::Duration::seconds(deadline.as_secs() as i64);
+            let stale = match self.storage.stale_pending_transactions(network, cutoff, EXPIRY_BATCH).await {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("tx expiry: failed to list stale sends on {}: {}", network, e);
+                    report.errors += 1;
+                    continue;
+                }
+            };
+            report.checked += stale.len();
+            for tx in &stale {
+                match self.expire_if_dropped(tx, deadline).await {
+                    Ok(Some(released)) => {
+                        report.expired += 1;
+                        report.nonces_released += usize::from(released);
+                    }
+                    Ok(None) => report.still_known += 1,
+                    Err(e) => {
+                        warn!("tx expiry: {} on {}: {}", tx.tx_hash, network, e);
+                        report.errors += 1;
+                    }
+                }
+            }
+        }
+        if report.expired > 0 {
+            info!("tx expiry: {} expired, {} nonces released", report.expired, report.nonces_released);
+        }
+        report
+    }
+
+    /// `Some(nonce released)` once expired, `None` while the node still has it.
+    async fn expire_if_dropped(&self, tx: &TransactionRecord, deadline: &Duration) -> anyhow::Result<Option<bool>> {
+        let hash: H256 = tx.tx_hash.parse().map_err(|_| anyhow::anyhow!("invalid transaction hash"))?;
+        // final on-chain check: mined or still in the mempool is not expired
+        if self.chain.transaction_receipt(&tx.network, hash).await?.is_some()
+            || self.chain.transaction_known(&tx.network, hash).await?
+        {
+            return Ok(None);
+        }
+        let reason = format!("not mined or in the mempool {}s after broadcast", deadline.as_secs());
+        if !self.storage.expire_transaction(&tx.id, &reason).await? {
+            // confirmed or rechecked concurrently
+            return Ok(None);
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.record_transaction_expired(&tx.network);
+        }
+
+        // managed sends share the transaction id with their intent; sends we
+        // only recorded have none and no nonce of ours to give back
+        let released = self.storage.release_signing_intent(&tx.id, INTENT_RECORDED, "transaction expired").await?;
+        info!(
+            "tx expiry: {} on {} expired after {}s{}",
+            tx.tx_hash,
+            tx.network,
+            (Utc::now() - tx.created_at).num_seconds(),
+            if released { ", nonce released" } else { "" }
+        );
+        Ok(Some(released))
+    }
+}
@@ -0,0 +1,174 @@
+//! src/ops/wal.rs
+//!
+//! Periodic WAL checkpoints and database size gauges.
+//!
+//! Each cycle samples the database file, WAL size, page count and freelist
+//! into the metrics registry and issues a `TRUNCATE` checkpoint. The
+//! checkpoint is deferred while the writer pool is busy; when a reader still
+//! holds an old snapshot it is retried a few times and then left for the next
+//! cycle rather than stalling writers behind it.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::core::config::WalConfig;
+use crate::monitoring::WalletMetrics;
+use crate::ops::jobs::{Job, Schedule};
+use crate::ops::leases::LeaderLease;
+use crate::storage::{CheckpointOutcome, DatabaseFileStats, WalSettings, WalletStorage};
+
+/// Scheduler lease job name
+pub const WAL_CHECKPOINT_JOB: &str = "wal_checkpoint";
+
+/// Outcome of one checkpoint attempt.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CheckpointReport {
+    /// Not attempted: more writer connections were in use than configured
+    pub deferred: bool,
+    pub attempts: u32,
+    /// Result of the last attempt; `busy` when every retry was blocked
+    pub outcome: Option<CheckpointOutcome>,
+    pub wal_bytes_before: u64,
+    /// File sizes sampled after the checkpoint
+    pub stats: DatabaseFileStats,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CheckpointError {
+    #[error("a WAL checkpoint is already running")]
+    InProgress,
+    #[error("WAL checkpoint failed: {0:#}")]
+    Failed(#[from] anyhow::Error),
+}
+
+impl From<&WalConfig> for WalSettings {
+    fn from(config: &WalConfig) -> Self {
+        WalSettings {
+            journal_size_limit: config.journal_size_limit_bytes,
+            wal_autocheckpoint: config.wal_autocheckpoint_pages,
+        }
+    }
+}
+
+pub struct WalMaintenance {
+    config: WalConfig,
+    storage: Arc<WalletStorage>,
+    metrics: Arc<WalletMetrics>,
+    running: Mutex<()>,
+    lease: Option<LeaderLease>,
+}
+
+impl WalMaintenance {
+    pub fn new(config: WalConfig, storage: Arc<WalletStorage>, metrics: Arc<WalletMetrics>) -> Self {
+        Self { config, storage, metrics, running: Mutex::new(()), lease: None }
+    }
+
+    pub fn with_lease(mut self, lease: LeaderLease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    pub fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.checkpoint_interval_secs.max(1))
+    }
+
+    pub fn config(&self) -> &WalConfig {
+        &self.config
+    }
+
+    /// Samples file sizes into the database gauges.
+    pub async fn sample(&self) -> Result<DatabaseFileStats> {
+        let stats = self.storage.database_file_stats().await?;
+        self.record(&stats);
+        Ok(stats)
+    }
+
+    fn record(&self, stats: &DatabaseFileStats) {
+        self.metrics.set_database_file_stats(stats.db_bytes, stats.wal_bytes, stats.page_count, stats.freelist_count);
+    }
+
+    /// Checkpoints unless one is already running. `force` skips the
+    /// busy-pool check (manual runs); blocked readers are still retried.
+    pub async fn try_checkpoint(&self, force: bool) -> Result<CheckpointReport, CheckpointError> {
+        let _guard = self.running.try_lock().map_err(|_| CheckpointError::InProgress)?;
+        Ok(self.checkpoint_locked(force).await?)
+    }
+
+    async fn checkpoint_locked(&self, force: bool) -> Result<CheckpointReport> {
+        let mut session = self.storage.wal_session().await?;
+        let in_use = session.others_in_use();
+        let before = session.file_stats().await?;
+        self.record(&before);
+        let mut report = CheckpointReport { wal_bytes_before: before.wal_bytes, ..Default::default() };
+
+        if !force && in_use > self.config.busy_connections as usize {
+            debug!("WAL checkpoint deferred: {} writer connections in use", in_use);
+            self.metrics.record_wal_checkpoint("deferred");
+            report.deferred = true;
+            report.stats = before;
+            return Ok(report);
+        }
+
+        loop {
+            report.attempts += 1;
+            let outcome = session.checkpoint_truncate().await?;
+            report.outcome = Some(outcome);
+            if !outcome.busy || report.attempts > self.config.checkpoint_retries {
+                break;
+            }
+            // a long-running read holds an old snapshot; give it time to finish
+            tokio::time::sleep(Duration::from_millis(self.config.retry_delay_ms)).await;
+        }
+
+        report.stats = session.file_stats().await?;
+        self.record(&report.stats);
+        if report.outcome.is_some_and(|o| o.busy) {
+            warn!("WAL checkpoint blocked by readers after {} attempts", report.attempts);
+            self.metrics.record_wal_checkpoint("busy");
+        } else {
+            self.metrics.record_wal_checkpoint("completed");
+            if report.wal_bytes_before > report.stats.wal_bytes {
+                info!("WAL checkpoint: {} -> {} bytes", report.wal_bytes_before, report.stats.wal_bytes);
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[async_trait]
+impl Job for WalMaintenance {
+    fn name(&self) -> &str {
+        WAL_CHECKPOINT_JOB
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: self.interval(), immediate: true }
+    }
+
+    fn lease(&self) -> Option<&LeaderLease> {
+        self.lease.as_ref()
+    }
+
+    /// A manual checkpoint in progress covers this cycle.
+    async fn run(&self, _cancel: CancellationToken) -> Result<()> {
+        match self.try_checkpoint(false).await {
+            Ok(report) => {
+                debug!("WAL checkpoint cycle: {:?}", report);
+                Ok(())
+            }
+            Err(CheckpointError::InProgress) => Ok(()),
+            Err(CheckpointError::Failed(e)) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! TTL cache in front of a [`PriceFeed`]
+//!
+//! - fresh (younger than `ttl`): served from memory
+//! - stale (within `stale` after that): served from memory at once while one
+//!   background request refreshes it, so a slow upstream never holds up a
+//!   response that already has a usable price
+//! - older or missing: the caller waits for the upstream
+//!
+//! Concurrent lookups of the same pair share one upstream request.
+
+use async_trait::async_trait;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use super::{Price, PriceError, PriceFeed};
+use crate::core::config::PricingConfig;
+use crate::ops::incidents::spawn_named;
+
+/// `(BASE, quote)`
+type Pair = (String, String);
+type Flight = Shared<BoxFuture<'static, Result<Price, PriceError>>>;
+
+struct Entry {
+    price: Price,
+    fetched_at: Instant,
+}
+
+struct Inner {
+    feed: Arc<dyn PriceFeed>,
+    entries: Mutex<HashMap<Pair, Entry>>,
+    in_flight: Mutex<HashMap<Pair, Flight>>,
+}
+
+pub struct CachedPriceFeed {
+    inner: Arc<Inner>,
+    ttl: Duration,
+    stale: Duration,
+}
+
+impl CachedPriceFeed {
+    pub fn new(feed: Arc<dyn PriceFeed>, ttl: Duration, stale: Duration) -> Self {
+        Self {
+            inner: Arc::new(Inner { feed, entries: Mutex::new(HashMap::new()), in_flight: Mutex::new(HashMap::new()) }),
+            ttl,
+            stale,
+        }
+    }
+
+    pub fn from_config(feed: Arc<dyn PriceFeed>, config: &PricingConfig) -> Self {
+        Self::new(feed, Duration::from_secs(config.cache_ttl_secs), Duration::from_secs(config.stale_secs))
+    }
+
+    /// The upstream request for `pair`, joining one already under way.
+    fn flight(&self, pair: &Pair) -> Flight {
+        let mut in_flight = self.inner.in_flight.lock();
+        if let Some(flight) = in_flight.get(pair) {
+            return flight.clone();
+        }
+        let inner = self.inner.clone();
+        let key = pair.clone();
+        let flight = async move {
+            let result = inner.feed.get_price(&key.0, &key.1).await;
+            if let Ok(price) = &result {
+                inner.entries.lock().insert(key.clone(), Entry { price: price.clone(), fetched_at: Instant::now() });
+            }
+            inner.in_flight.lock().remove(&key);
+            result
+        }
+        .boxed()
+        .shared();
+        in_flight.insert(pair.clone(), flight.clone());
+        flight
+    }
+}
+
+#[async_trait]
+impl PriceFeed for CachedPriceFeed {
+    async fn get_price(&self, base_symbol: &str, quote_currency: &str) -> Result<Price, PriceError> {
+        let pair = (base_symbol.to_ascii_uppercase(), quote_currency.to_ascii_lowercase());
+        let cached = self.inner.entries.lock().get(&pair).map(|e| (e.price.clone(), e.fetched_at.elapsed()));
+        match cached {
+            Some((price, age)) if age < self.ttl => Ok(price),
+            Some((price, age)) if age < self.ttl + self.stale => {
+                spawn_named("price_refresh", self.flight(&pair));
+                Ok(price)
+            }
+            _ => self.flight(&pair).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts upstream calls; each call takes `delay` and returns the call number as the price.
+    struct CountingFeed {
+        calls: AtomicUsize,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl PriceFeed for CountingFeed {
+        async fn get_price(&self, _base: &str, _quote: &str) -> Result<Price, PriceError> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(self.delay).await;
+            Ok(Price { price: Decimal::from(n as u64), as_of: Utc::now() })
+        }
+    }
+
+    fn cached(delay: Duration) -> (Arc<CountingFeed>, CachedPriceFeed) {
+        let feed = Arc::new(CountingFeed { calls: AtomicUsize::new(0), delay });
+        let cache = CachedPriceFeed::new(feed.clone(), Duration::from_secs(60), Duration::from_secs(600));
+        (feed, cache)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_concurrent_lookups_share_one_request() {
+        let (feed, cache) = cached(Duration::from_millis(100));
+        let lookups = (0..8).map(|_| cache.get_price("eth", "USD"));
+        let prices = futures::future::join_all(lookups).await;
+        assert_eq!(feed.calls.load(Ordering::SeqCst), 1);
+        assert!(prices.iter().all(|p| p.as_ref().unwrap().price == Decimal::ONE));
+        // 不同交易对各自请求
+        cache.get_price("BTC", "usd").await.unwrap();
+        assert_eq!(feed.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stale_price_is_served_while_refreshing() {
+        let (feed, cache) = cached(Duration::from_secs(5));
+        assert_eq!(cache.get_price("ETH", "usd").await.unwrap().price, Decimal::ONE);
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(cache.get_price("ETH", "usd").await.unwrap().price, Decimal::ONE);
+        assert_eq!(feed.calls.load(Ordering::SeqCst), 1);
+
+        // 过期：立即返回旧价格，后台刷新
+        tokio::time::advance(Duration::from_secs(60)).await;
+        let started = Instant::now();
+        assert_eq!(cache.get_price("ETH", "usd").await.unwrap().price, Decimal::ONE);
+        assert_eq!(started.elapsed(), Duration::ZERO);
+        tokio::time::sleep(Duration::from_secs(6)).await;
+        assert_eq!(feed.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.get_price("ETH", "usd").await.unwrap().price, Decimal::from(2u64));
+
+        // 超出 stale 窗口：等待上游
+        tokio::time::advance(Duration::from_secs(3600)).await;
+        assert_eq!(cache.get_price("ETH", "usd").await.unwrap().price, Decimal::from(3u64));
+    }
+}
@@ -0,0 +1,103 @@
+//! CoinGecko-compatible `simple/price` client
+
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::{parse_price, Price, PriceError, PriceFeed};
+use crate::core::config::PricingConfig;
+
+/// Built-in symbol → coin id map; `PricingConfig::coin_ids` adds to it
+const COIN_IDS: &[(&str, &str)] = &[
+    ("BTC", "bitcoin"),
+    ("ETH", "ethereum"),
+    ("MATIC", "matic-network"),
+    ("BNB", "binancecoin"),
+    ("USDT", "tether"),
+    ("USDC", "usd-coin"),
+    ("DAI", "dai"),
+];
+
+#[derive(Debug, Clone)]
+pub struct CoinGeckoFeed {
+    http: reqwest::Client,
+    base_url: String,
+    /// `(header, key)`
+    api_key: Option<(String, String)>,
+    coin_ids: HashMap<String, String>,
+}
+
+impl CoinGeckoFeed {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            coin_ids: COIN_IDS.iter().map(|(s, id)| (s.to_string(), id.to_string())).collect(),
+        }
+    }
+
+    /// Feed for `config`; the API key is read from `config.api_key_env`.
+    pub fn from_config(config: &PricingConfig) -> Self {
+        let mut feed = Self::new(&config.base_url).with_timeout(Duration::from_secs(config.request_timeout_secs.max(1)));
+        if let Some(key) = std::env::var(&config.api_key_env).ok().filter(|k| !k.trim().is_empty()) {
+            feed = feed.with_api_key(&config.api_key_header, key.trim());
+        }
+        for (symbol, id) in &config.coin_ids {
+            feed = feed.with_coin_id(symbol, id);
+        }
+        feed
+    }
+
+    pub fn with_api_key(mut self, header: &str, key: &str) -> Self {
+        self.api_key = Some((header.to_string(), key.to_string()));
+        self
+    }
+
+    pub fn with_coin_id(mut self, symbol: &str, id: &str) -> Self {
+        self.coin_ids.insert(symbol.to_ascii_uppercase(), id.to_string());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.http = reqwest::Client::builder().timeout(timeout).build().unwrap_or_default();
+        self
+    }
+}
+
+#[async_trait]
+impl PriceFeed for CoinGeckoFeed {
+    async fn get_price(&self, base_symbol: &str, quote_currency: &str) -> Result<Price, PriceError> {
+        let base = base_symbol.to_ascii_uppercase();
+        let quote = quote_currency.to_ascii_lowercase();
+        let id = self.coin_ids.get(&base).ok_or_else(|| PriceError::UnknownSymbol(base.clone()))?;
+
+        let mut request = self.http.get(format!("{}/simple/price", self.base_url)).query(&[
+            ("ids", id.as_str()),
+            ("vs_currencies", quote.as_str()),
+            ("include_last_updated_at", "true"),
+        ]);
+        if let Some((header, key)) = &self.api_key {
+            request = request.header(header.as_str(), key.as_str());
+        }
+        let response = request.send().await.map_err(|e| PriceError::Transport(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(PriceError::Status(response.status().as_u16()));
+        }
+        let body: serde_json::Value = response.json().await.map_err(|e| PriceError::Transport(e.to_string()))?;
+
+        // `{"ethereum": {"usd": 2000.5, "last_updated_at": 1700000000}}`
+        let entry = &body[id.as_str()];
+        let price = match &entry[quote.as_str()] {
+            serde_json::Value::Number(n) => parse_price(&n.to_string())?,
+            serde_json::Value::String(s) => parse_price(s)?,
+            _ => return Err(PriceError::NotQuoted { base, quote }),
+        };
+        let as_of = entry["last_updated_at"]
+            .as_i64()
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+            .unwrap_or_else(Utc::now);
+        Ok(Price { price, as_of })
+    }
+}
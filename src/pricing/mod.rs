@@ -0,0 +1,122 @@
+//! Fiat prices for balance and history display
+//!
+//! Prices are `rust_decimal::Decimal` end to end: the upstream JSON number is
+//! read from its text, and fiat values are computed by scaling the amount
+//! (minimal units or whole-unit decimal string) without going through `f64`.
+//! Price lookups are best-effort; callers omit fiat fields when they fail.
+
+mod cache;
+mod coingecko;
+
+pub use cache::CachedPriceFeed;
+pub use coingecko::CoinGeckoFeed;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ethers::types::U256;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// A quoted price: one whole `base` coin in `quote` currency
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Price {
+    pub price: Decimal,
+    /// When the upstream last updated the price
+    pub as_of: DateTime<Utc>,
+}
+
+/// Why a price could not be fetched
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PriceError {
+    #[error("no price source for {0}")]
+    UnknownSymbol(String),
+    #[error("price feed request failed: {0}")]
+    Transport(String),
+    #[error("price feed returned HTTP {0}")]
+    Status(u16),
+    #[error("price feed has no {quote} price for {base}")]
+    NotQuoted { base: String, quote: String },
+    #[error("price feed returned an unreadable price: {0}")]
+    InvalidPrice(String),
+}
+
+#[async_trait]
+pub trait PriceFeed: Send + Sync {
+    /// Price of `base_symbol` (e.g. `ETH`) in `quote_currency` (e.g. `usd`).
+    async fn get_price(&self, base_symbol: &str, quote_currency: &str) -> Result<Price, PriceError>;
+}
+
+/// Coin whose price values a network's native balance; `None` for testnets
+/// and networks without a market price.
+pub fn native_symbol(network: &str) -> Option<&'static str> {
+    match network {
+        "eth" => Some("ETH"),
+        "polygon" => Some("MATIC"),
+        "bsc" => Some("BNB"),
+        "btc" => Some("BTC"),
+        _ => None,
+    }
+}
+
+/// Parses a decimal price as printed by a JSON encoder, including exponent
+/// notation (`1.5e-7`).
+pub fn parse_price(text: &str) -> Result<Decimal, PriceError> {
+    let text = text.trim();
+    let parsed = if text.contains(['e', 'E']) { Decimal::from_scientific(text) } else { Decimal::from_str(text) };
+    match parsed {
+        Ok(price) if price.is_sign_negative() => Err(PriceError::InvalidPrice(text.to_string())),
+        Ok(price) => Ok(price),
+        Err(_) => Err(PriceError::InvalidPrice(text.to_string())),
+    }
+}
+
+/// Fiat value of `amount` minimal units of a coin with `decimals` decimals.
+/// `None` when the amount does not fit a `Decimal` (above ~7.9e28 units) or
+/// the product overflows.
+pub fn fiat_value(amount: U256, decimals: u32, price: Decimal) -> Option<Decimal> {
+    if amount.bits() > 96 || decimals > 28 {
+        return None;
+    }
+    let units = Decimal::from_i128_with_scale(amount.as_u128() as i128, decimals);
+    units.checked_mul(price).map(|v| v.normalize())
+}
+
+/// Fiat value of a whole-unit decimal amount such as `"1.5"`, as balances
+/// and history entries carry them.
+pub fn fiat_value_of(amount: &str, price: Decimal) -> Option<Decimal> {
+    Decimal::from_str(amount.trim()).ok()?.checked_mul(price).map(|v| v.normalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fiat_value_decimal_math() {
+        let price = Decimal::from_str("2000.50").unwrap();
+        // 1.5 ETH
+        let wei = U256::from(1_500_000_000_000_000_000u128);
+        assert_eq!(fiat_value(wei, 18, price).unwrap().to_string(), "3000.75");
+        // 1 wei of an 18-decimal token: exact, not rounded to zero
+        assert_eq!(fiat_value(U256::one(), 18, price).unwrap().to_string(), "0.0000000000000020005");
+        // 6-decimal stablecoin
+        let one_usdc = Decimal::from_str("0.9998").unwrap();
+        assert_eq!(fiat_value(U256::from(2_500_000u64), 6, one_usdc).unwrap().to_string(), "2.4995");
+        assert_eq!(fiat_value(U256::zero(), 18, price).unwrap(), Decimal::ZERO);
+        assert_eq!(fiat_value(U256::MAX, 18, price), None);
+
+        assert_eq!(fiat_value_of("1.5", price).unwrap().to_string(), "3000.75");
+        assert_eq!(fiat_value_of("0.000000000000000001", price).unwrap().to_string(), "0.0000000000000020005");
+        assert_eq!(fiat_value_of("0.0", price).unwrap(), Decimal::ZERO);
+        assert_eq!(fiat_value_of("abc", price), None);
+    }
+
+    #[test]
+    fn test_parse_price() {
+        assert_eq!(parse_price("2000.5").unwrap().to_string(), "2000.5");
+        assert_eq!(parse_price("1.5e-7").unwrap().to_string(), "0.00000015");
+        assert_eq!(parse_price("3E2").unwrap(), Decimal::from(300));
+        assert!(parse_price("-1").is_err());
+        assert!(parse_price("NaN").is_err());
+    }
+}
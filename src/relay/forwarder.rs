@@ -0,0 +1,160 @@
+//! EIP-2771 forwarder request: EIP-712 hashing, signer recovery and
+//! `execute` calldata.
+//!
+//! Matches the OpenZeppelin `MinimalForwarder` layout extended with a
+//! `validUntil` deadline:
+//!
+//! ```text
+//! ForwardRequest(address from,address to,uint256 value,uint256 gas,uint256 nonce,bytes data,uint256 validUntil)
+//! execute((address,address,uint256,uint256,uint256,bytes,uint256),bytes)
+//! ```
+
+use ethers::types::{Address, Bytes, Signature, H256, U256};
+use ethers::utils::keccak256;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::core::abi::{abi_encode_bytes, abi_pack, abi_word_u64, selector_from_signature};
+
+pub const FORWARD_REQUEST_TYPE: &str = "ForwardRequest(address from,address to,uint256 value,uint256 gas,uint256 nonce,bytes data,uint256 validUntil)";
+const EIP712_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+pub const EXECUTE_SIGNATURE: &str = "execute((address,address,uint256,uint256,uint256,bytes,uint256),bytes)";
+
+/// secp256k1 group order / 2; signatures above it are malleable and rejected
+/// by the forwarder's ECDSA library
+const SECP256K1_HALF_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/// The user's signed request, as produced by ethers-js `signTypedData`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForwardRequest {
+    pub from: Address,
+    pub to: Address,
+    #[serde(deserialize_with = "de_u256")]
+    pub value: U256,
+    #[serde(deserialize_with = "de_u256")]
+    pub gas: U256,
+    #[serde(deserialize_with = "de_u256")]
+    pub nonce: U256,
+    pub data: Bytes,
+    /// Unix seconds
+    #[serde(deserialize_with = "de_u256")]
+    pub valid_until: U256,
+}
+
+/// Accepts decimal strings (ethers-js default), `0x` hex strings and plain numbers
+pub(crate) fn de_u256<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Text(String),
+        Number(u64),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(n) => Ok(U256::from(n)),
+        Raw::Text(s) => {
+            let parsed = match s.strip_prefix("0x") {
+                Some(hex) => U256::from_str_radix(hex, 16).ok(),
+                None => U256::from_dec_str(&s).ok(),
+            };
+            parsed.ok_or_else(|| serde::de::Error::custom(format!("invalid uint256: {}", s)))
+        }
+    }
+}
+
+fn word(value: U256) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    value.to_big_endian(&mut out);
+    out
+}
+
+fn address_word(address: Address) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out[12..].copy_from_slice(address.as_bytes());
+    out
+}
+
+/// EIP-712 domain of one deployed forwarder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwarderDomain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub verifying_contract: Address,
+}
+
+impl ForwarderDomain {
+    pub fn separator(&self) -> [u8; 32] {
+        let mut buf = Vec::with_capacity(5 * 32);
+        buf.extend_from_slice(&keccak256(EIP712_DOMAIN_TYPE));
+        buf.extend_from_slice(&keccak256(&self.name));
+        buf.extend_from_slice(&keccak256(&self.version));
+        buf.extend_from_slice(&abi_word_u64(self.chain_id));
+        buf.extend_from_slice(&address_word(self.verifying_contract));
+        keccak256(buf)
+    }
+
+    /// `keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(request))`
+    pub fn digest(&self, request: &ForwardRequest) -> [u8; 32] {
+        let mut buf = Vec::with_capacity(2 + 64);
+        buf.extend_from_slice(b"\x19\x01");
+        buf.extend_from_slice(&self.separator());
+        buf.extend_from_slice(&request.struct_hash());
+        keccak256(buf)
+    }
+
+    /// Address that signed `request` under this domain. Malformed and
+    /// high-s signatures yield `None`.
+    pub fn recover_signer(&self, request: &ForwardRequest, signature: &[u8]) -> Option<Address> {
+        let signature = Signature::try_from(signature).ok()?;
+        if word(signature.s) > SECP256K1_HALF_ORDER {
+            return None;
+        }
+        signature.recover(H256::from(self.digest(request))).ok()
+    }
+}
+
+impl ForwardRequest {
+    pub fn struct_hash(&self) -> [u8; 32] {
+        keccak256(concat_words(&[
+            keccak256(FORWARD_REQUEST_TYPE),
+            address_word(self.from),
+            address_word(self.to),
+            word(self.value),
+            word(self.gas),
+            word(self.nonce),
+            keccak256(&self.data),
+            word(self.valid_until),
+        ]))
+    }
+}
+
+fn concat_words(words: &[[u8; 32]]) -> Vec<u8> {
+    words.iter().flatten().copied().collect()
+}
+
+/// Calldata of `execute(request, signature)` on the forwarder.
+pub fn encode_execute(request: &ForwardRequest, signature: &[u8]) -> Vec<u8> {
+    // tuple head: seven words, `data` is dynamic and sits right after them
+    let mut tuple = concat_words(&[
+        address_word(request.from),
+        address_word(request.to),
+        word(request.value),
+        word(request.gas),
+        word(request.nonce),
+        abi_word_u64(7 * 32),
+        word(request.valid_until),
+    ]);
+    tuple.extend_from_slice(&abi_encode_bytes(&request.data));
+
+    let mut calldata = abi_pack(
+        selector_from_signature(EXECUTE_SIGNATURE),
+        &[abi_word_u64(2 * 32), abi_word_u64((2 * 32 + tuple.len()) as u64)],
+    );
+    calldata.extend_from_slice(&tuple);
+    calldata.extend_from_slice(&abi_encode_bytes(signature));
+    calldata
+}
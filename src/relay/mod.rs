@@ -0,0 +1,306 @@
+//! Gasless meta-transaction relay (EIP-2771)
+//!
+//! Users without native gas sign a [`ForwardRequest`] off-chain. The relay
+//! checks it locally — deadline, EIP-712 signature against the configured
+//! forwarder, anomaly rules, per-sender quota — and then submits
+//! `execute(request, signature)` to the forwarder from a designated relayer
+//! wallet, which pays the gas. Every relay is recorded with the sender and
+//! the relayer's transaction hash.
+
+pub mod forwarder;
+
+use async_trait::async_trait;
+use ethers::types::{Address, U256};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::anomaly_detection::AnomalyDetector;
+use crate::core::amount::Amount;
+use crate::core::config::RelayConfig;
+use crate::core::errors::WalletError;
+use crate::core::wallet_manager::WalletManager;
+use crate::feature_flags::{FeatureFlags, Flag};
+use crate::storage::{NewMetaTxRelay, WalletStorage};
+
+pub use forwarder::{encode_execute, ForwardRequest, ForwarderDomain};
+
+/// Gas the forwarder spends around the inner call (signature check, nonce
+/// bump, calldata copy); added on top of `request.gas`.
+pub const EXECUTE_GAS_OVERHEAD: u64 = 60_000;
+
+/// Why a meta-transaction was not relayed. `code()` is part of the API contract.
+#[derive(Debug, thiserror::Error)]
+pub enum RelayError {
+    #[error("Meta-transaction relay is disabled")]
+    Disabled,
+    #[error("Invalid forward request: {0}")]
+    InvalidRequest(String),
+    #[error("Forward request expired at {valid_until}")]
+    Expired { valid_until: u64 },
+    #[error("Signature does not match the request sender")]
+    SignatureMismatch,
+    #[error("Relay quota exhausted: {limit} relays per {window_secs}s")]
+    QuotaExhausted { limit: u32, window_secs: u64 },
+    #[error("Forward request is already being relayed")]
+    Duplicate,
+    #[error("Blocked by anomaly detection: {0}")]
+    Blocked(String),
+    #[error("Relay submission failed: {0}")]
+    Submission(String),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+impl RelayError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RelayError::Disabled => "RELAY_DISABLED",
+            RelayError::InvalidRequest(_) => "INVALID_FORWARD_REQUEST",
+            RelayError::Expired { .. } => "META_TX_EXPIRED",
+            RelayError::SignatureMismatch => "SIGNATURE_MISMATCH",
+            RelayError::QuotaExhausted { .. } => "RELAY_QUOTA_EXHAUSTED",
+            RelayError::Duplicate => "META_TX_DUPLICATE",
+            RelayError::Blocked(_) => "ANOMALY_BLOCKED",
+            RelayError::Submission(_) => "RELAY_SUBMISSION_FAILED",
+            RelayError::Storage(_) => "DB_ERROR",
+        }
+    }
+}
+
+/// Broadcasts the forwarder call; abstracted so tests need no RPC node.
+#[async_trait]
+pub trait RelaySubmitter: Send + Sync {
+    async fn submit(
+        &self,
+        forwarder: Address,
+        value: U256,
+        gas_limit: u64,
+        calldata: Vec<u8>,
+    ) -> Result<String, WalletError>;
+}
+
+/// Submits from a hot wallet managed by [`WalletManager`]. The wallet
+/// password is read from the environment on every call.
+pub struct WalletRelaySubmitter {
+    wallet_manager: Arc<WalletManager>,
+    wallet: String,
+    network: String,
+    password_env: String,
+}
+
+impl WalletRelaySubmitter {
+    pub fn new(wallet_manager: Arc<WalletManager>, config: &RelayConfig) -> Self {
+        Self {
+            wallet_manager,
+            wallet: config.relayer_wallet.clone(),
+            network: config.network.clone(),
+            password_env: config.relayer_password_env.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl RelaySubmitter for WalletRelaySubmitter {
+    async fn submit(
+        &self,
+        forwarder: Address,
+        value: U256,
+        gas_limit: u64,
+        calldata: Vec<u8>,
+    ) -> Result<String, WalletError> {
+        let password = zeroize::Zeroizing::new(std::env::var(&self.password_env).map_err(|_| {
+            WalletError::ConfigError(format!("{} is not set", self.password_env))
+        })?);
+        self.wallet_manager
+            .send_contract_call(
+                &self.wallet,
+                forwarder,
+                value,
+                calldata,
+                gas_limit,
+                &self.network,
+                &password,
+            )
+            .await
+    }
+}
+
+/// Result of a successful relay
+#[derive(Debug, Clone, Serialize)]
+pub struct RelayReceipt {
+    pub relay_id: String,
+    pub from: String,
+    pub forwarder: String,
+    pub network: String,
+    /// Relayer's `execute` transaction
+    pub tx_hash: String,
+}
+
+pub struct RelayService {
+    config: RelayConfig,
+    /// `None` while the relay is disabled
+    domain: Option<ForwarderDomain>,
+    storage: Arc<WalletStorage>,
+    submitter: Arc<dyn RelaySubmitter>,
+    detector: Mutex<AnomalyDetector>,
+    /// `None` blocks whatever the detector flags
+    flags: Option<Arc<FeatureFlags>>,
+}
+
+fn hex_address(address: Address) -> String {
+    format!("{:?}", address)
+}
+
+impl RelayService {
+    /// Fails only when the relay is enabled with an unusable forwarder address.
+    pub fn new(
+        config: RelayConfig,
+        storage: Arc<WalletStorage>,
+        submitter: Arc<dyn RelaySubmitter>,
+    ) -> Result<Self, WalletError> {
+        let domain = if config.enabled {
+            let verifying_contract: Address = config.forwarder_address.parse().map_err(|_| {
+                WalletError::ConfigError(format!(
+                    "relay.forwarder_address is not an address: {:?}",
+                    config.forwarder_address
+                ))
+            })?;
+            Some(ForwarderDomain {
+                name: config.forwarder_name.clone(),
+                version: config.forwarder_version.clone(),
+                chain_id: config.chain_id,
+                verifying_contract,
+            })
+        } else {
+            None
+        };
+        Ok(Self {
+            config,
+            domain,
+            storage,
+            submitter,
+            detector: Mutex::new(AnomalyDetector::new()),
+            flags: None,
+        })
+    }
+
+    pub fn with_detector(mut self, detector: AnomalyDetector) -> Self {
+        self.detector = Mutex::new(detector);
+        self
+    }
+
+    /// Lets [`Flag::AnomalyBlocking`], scoped to the relayer wallet, turn
+    /// blocking off.
+    pub fn with_flags(mut self, flags: Arc<FeatureFlags>) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn config(&self) -> &RelayConfig {
+        &self.config
+    }
+
+    /// Checks `request` and submits it through the forwarder.
+    ///
+    /// Checks run cheapest first; the quota is only charged once the
+    /// request is known to be valid, and is refunded if submission fails.
+    pub async fn relay(
+        &self,
+        request: &ForwardRequest,
+        signature: &[u8],
+    ) -> Result<RelayReceipt, RelayError> {
+        let domain = self.domain.as_ref().ok_or(RelayError::Disabled)?;
+
+        if request.gas.is_zero() || request.gas > U256::from(self.config.max_gas) {
+            return Err(RelayError::InvalidRequest(format!(
+                "gas must be between 1 and {}",
+                self.config.max_gas
+            )));
+        }
+        if request.valid_until.is_zero() {
+            return Err(RelayError::InvalidRequest("validUntil is required".to_string()));
+        }
+        let now = chrono::Utc::now().timestamp() as u64;
+        if request.valid_until <= U256::from(now) {
+            return Err(RelayError::Expired { valid_until: request.valid_until.low_u64() });
+        }
+
+        if domain.recover_signer(request, signature) != Some(request.from) {
+            return Err(RelayError::SignatureMismatch);
+        }
+
+        // the detector scores in main units; approximate is fine for scoring
+        let value_native = Amount::native_u256("eth", request.value)
+            .map_err(|e| RelayError::InvalidRequest(format!("value: {}", e)))?
+            .to_f64_lossy();
+        let screened = self.detector.lock().await.validate_transaction(
+            &hex_address(request.to),
+            value_native,
+            None,
+            !request.data.is_empty(),
+        );
+        if let Err(e) = screened {
+            let blocking = match &self.flags {
+                Some(flags) => flags.is_enabled(Flag::AnomalyBlocking, Some(&self.config.relayer_wallet)).await,
+                None => true,
+            };
+            if blocking {
+                return Err(RelayError::Blocked(e.to_string()));
+            }
+            warn!("meta-transaction to {:?} not blocked (anomaly_blocking off): {}", request.to, e);
+        }
+
+        let from = hex_address(request.from);
+        let (limit, window_secs) = (self.config.quota_per_address, self.config.quota_window_secs);
+        if !self.storage.relay_consume_quota(&from, limit, window_secs).await? {
+            return Err(RelayError::QuotaExhausted { limit, window_secs });
+        }
+
+        let relay_id = uuid::Uuid::new_v4().to_string();
+        let forwarder = hex_address(domain.verifying_contract);
+        let reserved = self
+            .storage
+            .reserve_meta_tx_relay(&NewMetaTxRelay {
+                id: &relay_id,
+                from_address: &from,
+                to_address: &hex_address(request.to),
+                forwarder: &forwarder,
+                request_nonce: &request.nonce.to_string(),
+                network: &self.config.network,
+                relayer_wallet: &self.config.relayer_wallet,
+            })
+            .await;
+        if !matches!(reserved, Ok(true)) {
+            self.storage.relay_release_quota(&from).await?;
+            return Err(match reserved {
+                Err(e) => RelayError::Storage(e),
+                _ => RelayError::Duplicate,
+            });
+        }
+
+        let calldata = encode_execute(request, signature);
+        let gas_limit = request.gas.low_u64() + EXECUTE_GAS_OVERHEAD;
+        match self.submitter.submit(domain.verifying_contract, request.value, gas_limit, calldata).await
+        {
+            Ok(tx_hash) => {
+                self.storage.mark_meta_tx_submitted(&relay_id, &tx_hash).await?;
+                info!("Relayed meta-transaction {} from {}: {}", relay_id, from, tx_hash);
+                Ok(RelayReceipt {
+                    relay_id,
+                    from,
+                    forwarder,
+                    network: self.config.network.clone(),
+                    tx_hash,
+                })
+            }
+            Err(e) => {
+                warn!("Meta-transaction {} from {} failed to submit: {}", relay_id, from, e);
+                self.storage.mark_meta_tx_failed(&relay_id, &e.to_string()).await?;
+                self.storage.relay_release_quota(&from).await?;
+                Err(RelayError::Submission(e.to_string()))
+            }
+        }
+    }
+}
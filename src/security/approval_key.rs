@@ -0,0 +1,146 @@
+//! Signing key sealed for a send held for approval.
+//!
+//! The requester's password unlocks the signer once, when the send is held;
+//! the approver never has it. The key is therefore sealed then, the same way
+//! as the dead-man's [`super::sweep_capability`]: HKDF-SHA256 derives a
+//! one-off AES-256-GCM key from the process KEK and a random salt. The
+//! sealed key is stored next to the request, so any instance sharing the
+//! database can execute the approval, and a restart no longer loses it. The
+//! associated data is the held transaction itself (approval id, wallet,
+//! network, sender, recipient, value, token): a key does not open for
+//! another row, nor for a row whose recipient or value was edited in the
+//! database.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use ethers::core::k256::ecdsa::SigningKey;
+use ethers::signers::LocalWallet;
+use hkdf::Hkdf;
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::core::errors::WalletError;
+use crate::core::wallet::create::load_envelope_kek;
+
+const HKDF_INFO: &[u8] = b"approval-key/v1";
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// The transaction a sealed key is bound to
+#[derive(Debug, Clone, Copy)]
+pub struct HeldIntent<'a> {
+    pub approval_id: &'a str,
+    pub wallet_name: &'a str,
+    pub network: &'a str,
+    pub from: &'a str,
+    pub to: &'a str,
+    /// Wei (decimal)
+    pub value: &'a str,
+    /// ERC-20 contract; `None` for native transfers
+    pub token: Option<&'a str>,
+}
+
+impl HeldIntent<'_> {
+    fn aad(&self) -> Vec<u8> {
+        format!(
+            "approval/v1\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            self.approval_id,
+            self.wallet_name,
+            self.network,
+            self.from.to_ascii_lowercase(),
+            self.to.to_ascii_lowercase(),
+            self.value,
+            self.token.unwrap_or_default().to_ascii_lowercase()
+        )
+        .into_bytes()
+    }
+}
+
+#[derive(Clone)]
+pub struct ApprovalKey {
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+impl std::fmt::Debug for ApprovalKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApprovalKey").finish_non_exhaustive()
+    }
+}
+
+fn cipher(salt: &[u8]) -> Result<Aes256Gcm, WalletError> {
+    let master = Zeroizing::new(load_envelope_kek()?);
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(salt), &*master)
+        .expand(HKDF_INFO, &mut *key)
+        .map_err(|_| WalletError::CryptoError("Failed to derive approval key".to_string()))?;
+    Aes256Gcm::new_from_slice(&*key).map_err(|e| WalletError::CryptoError(format!("Failed to create cipher: {}", e)))
+}
+
+impl ApprovalKey {
+    /// Seals `signer`'s key for `intent`.
+    pub fn seal(intent: &HeldIntent<'_>, signer: &LocalWallet) -> Result<Self, WalletError> {
+        let secret = Zeroizing::new(signer.signer().to_bytes().to_vec());
+        let mut salt = vec![0u8; SALT_LEN];
+        let mut nonce = vec![0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let ciphertext = cipher(&salt)?
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: secret.as_slice(), aad: &intent.aad() })
+            .map_err(|_| WalletError::CryptoError("Failed to seal approval key".to_string()))?;
+        Ok(Self { salt, nonce, ciphertext })
+    }
+
+    pub fn open(&self, intent: &HeldIntent<'_>) -> Result<LocalWallet, WalletError> {
+        if self.nonce.len() != NONCE_LEN {
+            return Err(WalletError::CryptoError("Corrupt approval key".to_string()));
+        }
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let secret = Zeroizing::new(
+            cipher(&self.salt)?
+                .decrypt(Nonce::from_slice(&self.nonce), Payload { msg: &self.ciphertext, aad: &intent.aad() })
+                .map_err(|_| WalletError::SecurityError("Approval key does not match the held send".to_string()))?,
+        );
+        let key = SigningKey::from_slice(&secret)
+            .map_err(|e| WalletError::CryptoError(format!("Invalid sealed key: {}", e)))?;
+        Ok(LocalWallet::from(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::Signer;
+
+    #[test]
+    fn test_key_opens_only_for_its_intent() {
+        std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+        let signer = LocalWallet::from(SigningKey::from_slice(&[7u8; 32]).unwrap());
+        let intent = HeldIntent {
+            approval_id: "apr-1",
+            wallet_name: "treasury",
+            network: "eth",
+            from: "0x00000000000000000000000000000000000000a1",
+            to: "0x00000000000000000000000000000000000000B0",
+            value: "2000000000000000000",
+            token: None,
+        };
+        let sealed = ApprovalKey::seal(&intent, &signer).unwrap();
+
+        let to = "0x00000000000000000000000000000000000000b0";
+        assert_eq!(sealed.open(&HeldIntent { to, ..intent }).unwrap().address(), signer.address());
+        let redirected = HeldIntent { to: "0x00000000000000000000000000000000000000c0", ..intent };
+        assert!(matches!(sealed.open(&redirected), Err(WalletError::SecurityError(_))));
+        let raised = HeldIntent { value: "9000000000000000000", ..intent };
+        assert!(matches!(sealed.open(&raised), Err(WalletError::SecurityError(_))));
+        let moved = HeldIntent { approval_id: "apr-2", ..intent };
+        assert!(matches!(sealed.open(&moved), Err(WalletError::SecurityError(_))));
+        let as_token = HeldIntent { token: Some("0x00000000000000000000000000000000000000d0"), ..intent };
+        assert!(matches!(sealed.open(&as_token), Err(WalletError::SecurityError(_))));
+    }
+}
@@ -101,12 +101,10 @@ impl ComplianceChecker {
 
         // Transaction-type specific checks
         match transaction_type {
-            TransactionType::Bridge => {
-                if amount > self.max_transaction_limit * 0.5 {
-                    return Ok(ComplianceResult::RequiresApproval(
-                        "Large bridge transactions require approval".to_string(),
-                    ));
-                }
+            TransactionType::Bridge if amount > self.max_transaction_limit * 0.5 => {
+                return Ok(ComplianceResult::RequiresApproval(
+                    "Large bridge transactions require approval".to_string(),
+                ));
             }
             TransactionType::Swap => {
                 // Placeholder for swap-specific checks
@@ -0,0 +1,106 @@
+//! Signing key sealed for a delegated spending session.
+//!
+//! The owner's password is only available when the delegation is created, so
+//! the wallet key is sealed then, much like the dead-man's
+//! [`super::sweep_capability`]. The difference is who may open it: the
+//! AES-256-GCM key is derived with HKDF-SHA256 from the process KEK *and*
+//! the plaintext delegation token, which the server never stores. A copy of
+//! the database plus the KEK is therefore not enough to sign; it takes a
+//! request that actually presents the token. The delegation id is bound as
+//! associated data so a sealed key cannot be moved to another row.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use ethers::core::k256::ecdsa::SigningKey;
+use ethers::signers::LocalWallet;
+use hkdf::Hkdf;
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::core::errors::WalletError;
+use crate::core::wallet::create::load_envelope_kek;
+
+const HKDF_INFO: &[u8] = b"delegation-key/v1";
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+#[derive(Clone)]
+pub struct DelegationKey {
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+impl std::fmt::Debug for DelegationKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DelegationKey").finish_non_exhaustive()
+    }
+}
+
+fn aad(delegation_id: &str) -> Vec<u8> {
+    format!("delegation/v1\n{}", delegation_id).into_bytes()
+}
+
+fn cipher(salt: &[u8], token: &str) -> Result<Aes256Gcm, WalletError> {
+    let master = Zeroizing::new(load_envelope_kek()?);
+    let mut ikm = Zeroizing::new(Vec::with_capacity(master.len() + token.len()));
+    ikm.extend_from_slice(&*master);
+    ikm.extend_from_slice(token.as_bytes());
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(salt), &ikm)
+        .expand(HKDF_INFO, &mut *key)
+        .map_err(|_| WalletError::CryptoError("Failed to derive delegation key".to_string()))?;
+    Aes256Gcm::new_from_slice(&*key).map_err(|e| WalletError::CryptoError(format!("Failed to create cipher: {}", e)))
+}
+
+impl DelegationKey {
+    /// Seals `secret_key` (32-byte secp256k1 scalar) for `delegation_id`,
+    /// openable only with `token`.
+    pub fn seal(delegation_id: &str, token: &str, secret_key: &[u8]) -> Result<Self, WalletError> {
+        let mut salt = vec![0u8; SALT_LEN];
+        let mut nonce = vec![0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let ciphertext = cipher(&salt, token)?
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: secret_key, aad: &aad(delegation_id) })
+            .map_err(|_| WalletError::CryptoError("Failed to seal delegation key".to_string()))?;
+        Ok(Self { salt, nonce, ciphertext })
+    }
+
+    pub fn open(&self, delegation_id: &str, token: &str) -> Result<LocalWallet, WalletError> {
+        if self.nonce.len() != NONCE_LEN {
+            return Err(WalletError::CryptoError("Corrupt delegation key".to_string()));
+        }
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let secret = Zeroizing::new(
+            cipher(&self.salt, token)?
+                .decrypt(Nonce::from_slice(&self.nonce), Payload { msg: &self.ciphertext, aad: &aad(delegation_id) })
+                .map_err(|_| WalletError::SecurityError("Delegation key does not open with this token".to_string()))?,
+        );
+        let key = SigningKey::from_slice(&secret)
+            .map_err(|e| WalletError::CryptoError(format!("Invalid sealed key: {}", e)))?;
+        Ok(LocalWallet::from(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::Signer;
+
+    #[test]
+    fn test_key_opens_only_with_its_token_and_id() {
+        std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+        let secret = [9u8; 32];
+        let sealed = DelegationKey::seal("dlg-1", "dlg_token", &secret).unwrap();
+
+        let signer = sealed.open("dlg-1", "dlg_token").unwrap();
+        assert_eq!(signer.address(), LocalWallet::from(SigningKey::from_slice(&secret).unwrap()).address());
+        assert!(matches!(sealed.open("dlg-1", "dlg_other"), Err(WalletError::SecurityError(_))));
+        assert!(matches!(sealed.open("dlg-2", "dlg_token"), Err(WalletError::SecurityError(_))));
+    }
+}
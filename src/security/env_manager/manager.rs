@@ -1,6 +1,7 @@
 //! 安全环境管理器核心实现
 
 use super::permissions::PermissionLevel;
+use crate::security::SecretVec;
 use lazy_static::lazy_static;
 
 lazy_static! {
@@ -8,6 +9,13 @@ lazy_static! {
     pub static ref SECURE_ENV_MANAGER: SecureEnvManager = SecureEnvManager::new();
 }
 
+/// 管理 API 密钥：主密钥与可选的第二把，轮换重叠期间两把都有效
+#[derive(Clone)]
+pub struct ApiKeys {
+    pub primary: SecretVec,
+    pub secondary: Option<SecretVec>,
+}
+
 /// 安全环境管理器
 pub struct SecureEnvManager {
     // 使用原有的 env_manager.rs 中的实现
@@ -25,6 +33,17 @@ impl SecureEnvManager {
         std::env::var(key).ok()
     }
 
+    /// fetch管理 API 密钥（`API_KEY` 与可选的 `API_KEY_SECONDARY`）；都未设置时为
+    /// `None`，只设置了第二把视为配置error（否则认证会被静默关闭）
+    pub fn get_api_keys(&self) -> anyhow::Result<Option<ApiKeys>> {
+        let secondary = super::secret_backend::api_key_secondary()?;
+        match super::secret_backend::api_key()? {
+            Some(primary) => Ok(Some(ApiKeys { primary, secondary })),
+            None if secondary.is_some() => anyhow::bail!("API_KEY_SECONDARY is set without API_KEY"),
+            None => Ok(None),
+        }
+    }
+
     /// 设置权限（占位符）
     pub fn set_permission(&self, _key: &str, _level: PermissionLevel) {
         // 实际实现在原 env_manager.rs 中
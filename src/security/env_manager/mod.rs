@@ -3,18 +3,22 @@
 //! 提供安全环境变量管理和权限控制
 //!
 //! ## 模块结构
+//! - `config_secrets` - config.toml 中的 `${secure:NAME}` 引用与内联密钥检查、迁移
 //! - `manager` - SecureEnvManager 核心
 //! - `permissions` - 权限级别和控制
+//! - `secret_backend` - 主密钥 / API 密钥的可插拔来源（env、文件、钥匙串、Vault）
 //! - `secure_env` - 安全环境变量访问
 //! - `validation` - 环境validate
 
+pub mod config_secrets;
 pub mod manager;
 pub mod permissions;
+pub mod secret_backend;
 pub mod secure_env;
 pub mod validation;
 
 // 重新导出核心类型
-pub use manager::SecureEnvManager;
+pub use manager::{ApiKeys, SecureEnvManager};
 pub use permissions::PermissionLevel;
 pub use secure_env::*;
 
@@ -0,0 +1,635 @@
+//! 可插拔密钥后端
+//!
+//! 主密钥（`WALLET_ENC_KEY`）与 API 密钥可以来自环境变量（默认，与之前相同）、
+//! 目录中的密钥文件、操作系统钥匙串或 HashiCorp Vault KV v2，由
+//! `[security.secret_backend]` 选择。
+//!
+//! 非环境变量后端在启动时读取一次（读不到主密钥拒绝启动），之后由
+//! `ops::secret_refresh` 按间隔刷新安装在进程里的 [`SecretStore`]。所有
+//! 使用主密钥的地方（wallet加密、envelope KEK、审计 MAC）都经过
+//! [`master_key_encoded`]；缓存超过 `max_staleness_secs` 未被后端确认时
+//! 返回 [`SecretBackendError::Stale`]，sign随之被拒绝，而不是继续使用可能
+//! 已被吊销的密钥。
+//!
+//! 管理 API 密钥的轮换通过 [`SecretStore::store_api_keys`] 写回后端（环境
+//! 变量后端只读），各实例在下一次刷新时换上新密钥。
+
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use crate::core::config::{SecretSourceConfig, VaultAuthConfig, VaultSecretConfig};
+use crate::security::secret::{vec_to_secret, SecretVec};
+use crate::security::ApiKeys;
+
+/// 主密钥（base64 编码的 32 字节）
+pub const MASTER_KEY: &str = "WALLET_ENC_KEY";
+/// 管理 API 密钥
+pub const API_KEY: &str = "API_KEY";
+/// 第二把管理 API 密钥，轮换重叠期间与 [`API_KEY`] 同样有效
+pub const API_KEY_SECONDARY: &str = "API_KEY_SECONDARY";
+
+/// 启动与每次刷新时读取的密钥，以及是否必需
+const SECRETS: &[(&str, bool)] = &[(MASTER_KEY, true), (API_KEY, false), (API_KEY_SECONDARY, false)];
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretBackendError {
+    #[error("{name} not set in the {backend} secret backend")]
+    NotSet { name: String, backend: &'static str },
+    #[error("{backend} secret backend is misconfigured: {reason}")]
+    Misconfigured { backend: &'static str, reason: String },
+    #[error("{backend} secret backend is unavailable: {reason}")]
+    Unavailable { backend: &'static str, reason: String },
+    #[error(
+        "{name} was last confirmed by the {backend} secret backend {age_secs}s ago (limit {max_secs}s); \
+         refusing to use a possibly revoked key"
+    )]
+    Stale { name: String, backend: &'static str, age_secs: u64, max_secs: u64 },
+    #[error("the master key changed in the {backend} secret backend and was not adopted: {reason}")]
+    RotationPending { backend: &'static str, reason: String },
+}
+
+/// 密钥来源
+#[async_trait]
+pub trait SecretBackend: Send + Sync {
+    /// `env`、`file`、`keychain` 或 `vault`
+    fn kind(&self) -> &'static str;
+
+    /// `name` 的当前值；后端中没有时为 `None`
+    async fn fetch(&self, name: &str) -> Result<Option<SecretVec>, SecretBackendError>;
+
+    /// 写入 `values`（API 密钥轮换）；默认只读
+    async fn store(&self, values: &[(&str, &SecretVec)]) -> Result<(), SecretBackendError> {
+        let _ = values;
+        Err(SecretBackendError::Misconfigured {
+            backend: self.kind(),
+            reason: "this backend is read-only; rotated keys cannot be saved".to_string(),
+        })
+    }
+}
+
+/// 环境变量（默认后端）
+pub struct EnvSecretBackend;
+
+#[async_trait]
+impl SecretBackend for EnvSecretBackend {
+    fn kind(&self) -> &'static str {
+        "env"
+    }
+
+    async fn fetch(&self, name: &str) -> Result<Option<SecretVec>, SecretBackendError> {
+        Ok(std::env::var(name).ok().map(|value| vec_to_secret(value.into_bytes())))
+    }
+}
+
+/// 目录中以密钥名命名的文件（如 `/run/secrets/WALLET_ENC_KEY`），去掉末尾空白
+pub struct FileSecretBackend {
+    directory: PathBuf,
+}
+
+impl FileSecretBackend {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self { directory: directory.into() }
+    }
+
+    fn misconfigured(reason: String) -> SecretBackendError {
+        SecretBackendError::Misconfigured { backend: "file", reason }
+    }
+}
+
+#[async_trait]
+impl SecretBackend for FileSecretBackend {
+    fn kind(&self) -> &'static str {
+        "file"
+    }
+
+    async fn fetch(&self, name: &str) -> Result<Option<SecretVec>, SecretBackendError> {
+        let path = self.directory.join(name);
+        let metadata = match tokio::fs::metadata(&path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(SecretBackendError::Unavailable {
+                    backend: "file",
+                    reason: format!("{}: {}", path.display(), e),
+                })
+            }
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = metadata.permissions().mode();
+            if mode & 0o077 != 0 {
+                return Err(Self::misconfigured(format!(
+                    "{} is accessible to group or others (mode {:o}); chmod 600 it",
+                    path.display(),
+                    mode & 0o777
+                )));
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = metadata;
+        let raw = Zeroizing::new(tokio::fs::read(&path).await.map_err(|e| SecretBackendError::Unavailable {
+            backend: "file",
+            reason: format!("{}: {}", path.display(), e),
+        })?);
+        let text =
+            std::str::from_utf8(&raw).map_err(|_| Self::misconfigured(format!("{} is not UTF-8", path.display())))?;
+        Ok(Some(vec_to_secret(text.trim_end().as_bytes().to_vec())))
+    }
+
+    /// 先写同目录的临时文件（600）再改名，读取方不会看到写了一半的密钥
+    async fn store(&self, values: &[(&str, &SecretVec)]) -> Result<(), SecretBackendError> {
+        for (name, value) in values {
+            let path = self.directory.join(name);
+            let tmp = self.directory.join(format!(".{}.tmp", name));
+            let unavailable = |e: std::io::Error| SecretBackendError::Unavailable {
+                backend: "file",
+                reason: format!("{}: {}", path.display(), e),
+            };
+            let mut options = tokio::fs::OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            options.mode(0o600);
+            let mut file = options.open(&tmp).await.map_err(unavailable)?;
+            tokio::io::AsyncWriteExt::write_all(&mut file, value).await.map_err(unavailable)?;
+            file.sync_all().await.map_err(unavailable)?;
+            tokio::fs::rename(&tmp, &path).await.map_err(unavailable)?;
+        }
+        Ok(())
+    }
+}
+
+/// 操作系统钥匙串（macOS Keychain、Windows Credential Manager、Secret Service）
+#[cfg(feature = "os-keychain")]
+pub struct KeychainSecretBackend {
+    service: String,
+    /// 密钥名 → 钥匙串条目名；未列出的用密钥名本身
+    entries: HashMap<String, String>,
+}
+
+#[cfg(feature = "os-keychain")]
+impl KeychainSecretBackend {
+    pub fn new(service: impl Into<String>, entries: HashMap<String, String>) -> Self {
+        Self { service: service.into(), entries }
+    }
+}
+
+#[cfg(feature = "os-keychain")]
+#[async_trait]
+impl SecretBackend for KeychainSecretBackend {
+    fn kind(&self) -> &'static str {
+        "keychain"
+    }
+
+    async fn fetch(&self, name: &str) -> Result<Option<SecretVec>, SecretBackendError> {
+        let service = self.service.clone();
+        let entry = self.entries.get(name).cloned().unwrap_or_else(|| name.to_string());
+        // 钥匙串 API 是阻塞的，可能弹出系统授权
+        let result = tokio::task::spawn_blocking(move || {
+            keyring::Entry::new(&service, &entry).and_then(|entry| entry.get_password())
+        })
+        .await
+        .map_err(|e| SecretBackendError::Unavailable { backend: "keychain", reason: e.to_string() })?;
+        match result {
+            Ok(password) => Ok(Some(vec_to_secret(password.into_bytes()))),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(SecretBackendError::Unavailable { backend: "keychain", reason: e.to_string() }),
+        }
+    }
+
+    async fn store(&self, values: &[(&str, &SecretVec)]) -> Result<(), SecretBackendError> {
+        for (name, value) in values {
+            let service = self.service.clone();
+            let entry = self.entries.get(*name).cloned().unwrap_or_else(|| name.to_string());
+            let password = Zeroizing::new(String::from_utf8_lossy(value).into_owned());
+            tokio::task::spawn_blocking(move || {
+                keyring::Entry::new(&service, &entry).and_then(|entry| entry.set_password(&password))
+            })
+            .await
+            .map_err(|e| SecretBackendError::Unavailable { backend: "keychain", reason: e.to_string() })?
+            .map_err(|e| SecretBackendError::Unavailable { backend: "keychain", reason: e.to_string() })?;
+        }
+        Ok(())
+    }
+}
+
+enum VaultAuth {
+    Token(SecretVec),
+    AppRole { mount: String, role_id: SecretVec, secret_id: SecretVec },
+}
+
+/// HashiCorp Vault KV v2：一个 secret 路径，每个密钥一个字段
+pub struct VaultSecretBackend {
+    http: reqwest::Client,
+    address: String,
+    mount: String,
+    path: String,
+    namespace: Option<String>,
+    auth: VaultAuth,
+    /// 密钥名 → 字段名；未列出的用小写的密钥名
+    fields: HashMap<String, String>,
+    /// AppRole 登录得到的 client token
+    token: tokio::sync::Mutex<Option<SecretVec>>,
+}
+
+impl VaultSecretBackend {
+    /// 凭据从配置中指定的环境变量读取，缺少时报配置error
+    pub fn new(config: &VaultSecretConfig) -> Result<Self, SecretBackendError> {
+        let misconfigured = |reason: String| SecretBackendError::Misconfigured { backend: "vault", reason };
+        let from_env = |var: &str| {
+            std::env::var(var)
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| vec_to_secret(v.trim().as_bytes().to_vec()))
+                .ok_or_else(|| misconfigured(format!("{} is not set", var)))
+        };
+        if config.address.trim().is_empty() || config.path.trim().is_empty() {
+            return Err(misconfigured("address and path are required".to_string()));
+        }
+        let auth = match &config.auth {
+            VaultAuthConfig::Token { token_env } => VaultAuth::Token(from_env(token_env)?),
+            VaultAuthConfig::AppRole { mount, role_id_env, secret_id_env } => VaultAuth::AppRole {
+                mount: mount.trim_matches('/').to_string(),
+                role_id: from_env(role_id_env)?,
+                secret_id: from_env(secret_id_env)?,
+            },
+        };
+        let mut builder = reqwest::Client::builder().timeout(Duration::from_secs(config.timeout_secs.max(1)));
+        if let Some(ca_cert) = &config.ca_cert {
+            let pem =
+                std::fs::read(ca_cert).map_err(|e| misconfigured(format!("ca_cert {}: {}", ca_cert.display(), e)))?;
+            let cert = reqwest::Certificate::from_pem(&pem)
+                .map_err(|e| misconfigured(format!("ca_cert {}: {}", ca_cert.display(), e)))?;
+            builder = builder.add_root_certificate(cert);
+        }
+        let http = builder.build().map_err(|e| misconfigured(e.to_string()))?;
+        Ok(Self {
+            http,
+            address: config.address.trim_end_matches('/').to_string(),
+            mount: config.mount.trim_matches('/').to_string(),
+            path: config.path.trim_matches('/').to_string(),
+            namespace: config.namespace.clone(),
+            auth,
+            fields: config.fields.clone(),
+            token: tokio::sync::Mutex::new(None),
+        })
+    }
+
+    fn unavailable(reason: String) -> SecretBackendError {
+        SecretBackendError::Unavailable { backend: "vault", reason }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self.http.request(method, format!("{}/v1/{}", self.address, path));
+        match &self.namespace {
+            Some(namespace) => request.header("X-Vault-Namespace", namespace),
+            None => request,
+        }
+    }
+
+    /// 当前 token；`renew` 时 AppRole 重新登录
+    async fn token(&self, renew: bool) -> Result<SecretVec, SecretBackendError> {
+        let (mount, role_id, secret_id) = match &self.auth {
+            VaultAuth::Token(token) => return Ok(token.clone()),
+            VaultAuth::AppRole { mount, role_id, secret_id } => (mount, role_id, secret_id),
+        };
+        let mut cached = self.token.lock().await;
+        if let Some(token) = cached.as_ref().filter(|_| !renew) {
+            return Ok(token.clone());
+        }
+        let utf8 = |value: &SecretVec| Zeroizing::new(String::from_utf8_lossy(value).into_owned());
+        let (role_id, secret_id) = (utf8(role_id), utf8(secret_id));
+        let body = serde_json::json!({ "role_id": role_id.as_str(), "secret_id": secret_id.as_str() });
+        let response = self
+            .request(reqwest::Method::POST, &format!("auth/{}/login", mount))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| Self::unavailable(format!("AppRole login failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(Self::unavailable(format!("AppRole login returned HTTP {}", response.status())));
+        }
+        let body: serde_json::Value =
+            response.json().await.map_err(|e| Self::unavailable(format!("AppRole login response: {}", e)))?;
+        let token = body["auth"]["client_token"]
+            .as_str()
+            .map(|token| vec_to_secret(token.as_bytes().to_vec()))
+            .ok_or_else(|| Self::unavailable("AppRole login response has no client_token".to_string()))?;
+        *cached = Some(token.clone());
+        Ok(token)
+    }
+
+    /// 带 token 发送请求；AppRole token 过期（403）时重新登录再发一次
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<reqwest::Response, SecretBackendError> {
+        let action = if method == reqwest::Method::GET { "reading" } else { "writing" };
+        for attempt in 0..2 {
+            let token = self.token(attempt > 0).await?;
+            let token = Zeroizing::new(String::from_utf8_lossy(&token).into_owned());
+            let mut request = self.request(method.clone(), path).header("X-Vault-Token", token.as_str());
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            let response =
+                request.send().await.map_err(|e| Self::unavailable(format!("{} {}: {}", action, path, e)))?;
+            if response.status() == reqwest::StatusCode::FORBIDDEN
+                && attempt == 0
+                && matches!(self.auth, VaultAuth::AppRole { .. })
+            {
+                continue;
+            }
+            return Ok(response);
+        }
+        Err(Self::unavailable(format!("{} {} was refused after a fresh login", action, path)))
+    }
+
+    /// secret 的全部字段与当前版本；路径不存在时为空、版本为 0
+    async fn read(&self) -> Result<(serde_json::Map<String, serde_json::Value>, u64), SecretBackendError> {
+        let path = format!("{}/data/{}", self.mount, self.path);
+        let response = self.send(reqwest::Method::GET, &path, None).await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok((serde_json::Map::new(), 0));
+        }
+        if !status.is_success() {
+            return Err(Self::unavailable(format!("reading {} returned HTTP {}", path, status)));
+        }
+        let mut body: serde_json::Value =
+            response.json().await.map_err(|e| Self::unavailable(format!("reading {}: {}", path, e)))?;
+        let version = body["data"]["metadata"]["version"].as_u64().unwrap_or(0);
+        match body["data"]["data"].take() {
+            serde_json::Value::Object(fields) => Ok((fields, version)),
+            _ => Err(Self::unavailable(format!("{} is not a KV v2 secret", path))),
+        }
+    }
+
+    fn field(&self, name: &str) -> String {
+        self.fields.get(name).cloned().unwrap_or_else(|| name.to_lowercase())
+    }
+}
+
+#[async_trait]
+impl SecretBackend for VaultSecretBackend {
+    fn kind(&self) -> &'static str {
+        "vault"
+    }
+
+    async fn fetch(&self, name: &str) -> Result<Option<SecretVec>, SecretBackendError> {
+        let field = self.field(name);
+        let (mut fields, _) = self.read().await?;
+        let value = match fields.remove(&field) {
+            Some(serde_json::Value::String(value)) => Zeroizing::new(value),
+            Some(_) => {
+                return Err(SecretBackendError::Misconfigured {
+                    backend: "vault",
+                    reason: format!("field {} of {} is not a string", field, self.path),
+                })
+            }
+            None => return Ok(None),
+        };
+        Ok(Some(vec_to_secret(value.as_bytes().to_vec())))
+    }
+
+    /// 读出全部字段、替换 `values` 后写成新版本。KV v2 的 check-and-set 保证
+    /// 期间被别人写过时失败，而不是覆盖掉别的字段
+    async fn store(&self, values: &[(&str, &SecretVec)]) -> Result<(), SecretBackendError> {
+        let (mut fields, version) = self.read().await?;
+        for (name, value) in values {
+            let value = String::from_utf8(value.to_vec()).map_err(|_| SecretBackendError::Misconfigured {
+                backend: "vault",
+                reason: format!("{} is not UTF-8", name),
+            })?;
+            fields.insert(self.field(name), serde_json::Value::String(value));
+        }
+        let path = format!("{}/data/{}", self.mount, self.path);
+        let body = serde_json::json!({ "options": { "cas": version }, "data": fields });
+        let response = self.send(reqwest::Method::POST, &path, Some(&body)).await?;
+        if !response.status().is_success() {
+            return Err(Self::unavailable(format!("writing {} returned HTTP {}", path, response.status())));
+        }
+        Ok(())
+    }
+}
+
+/// 按配置构造后端
+pub fn backend_from_config(config: &SecretSourceConfig) -> Result<Arc<dyn SecretBackend>, SecretBackendError> {
+    Ok(match config {
+        SecretSourceConfig::Env => Arc::new(EnvSecretBackend),
+        SecretSourceConfig::File { directory } => Arc::new(FileSecretBackend::new(directory.clone())),
+        #[cfg(feature = "os-keychain")]
+        SecretSourceConfig::Keychain { service, entries } => {
+            Arc::new(KeychainSecretBackend::new(service.clone(), entries.clone()))
+        }
+        #[cfg(not(feature = "os-keychain"))]
+        SecretSourceConfig::Keychain { .. } => {
+            return Err(SecretBackendError::Misconfigured {
+                backend: "keychain",
+                reason: "this build does not include the os-keychain feature".to_string(),
+            })
+        }
+        SecretSourceConfig::Vault(vault) => Arc::new(VaultSecretBackend::new(vault)?),
+    })
+}
+
+struct CachedSecret {
+    value: SecretVec,
+    /// 后端最近一次返回同一个值的时间
+    confirmed_at: Instant,
+}
+
+/// 后端密钥的进程内缓存
+pub struct SecretStore {
+    backend: Arc<dyn SecretBackend>,
+    max_staleness: Option<Duration>,
+    secrets: RwLock<HashMap<&'static str, CachedSecret>>,
+    /// 后端换了主密钥但未被采用的原因
+    rotation_pending: RwLock<Option<String>>,
+}
+
+impl SecretStore {
+    /// 启动检查：读取所有密钥，缺少主密钥或后端不可用时失败
+    pub async fn open(
+        backend: Arc<dyn SecretBackend>,
+        max_staleness: Option<Duration>,
+    ) -> Result<Self, SecretBackendError> {
+        let store =
+            Self { backend, max_staleness, secrets: RwLock::new(HashMap::new()), rotation_pending: RwLock::new(None) };
+        store.refresh().await?;
+        info!("secrets loaded from the {} backend", store.backend.kind());
+        Ok(store)
+    }
+
+    pub fn backend_kind(&self) -> &'static str {
+        self.backend.kind()
+    }
+
+    /// 缓存的 `name`；主密钥轮换未采用或超过有效期时失败
+    pub fn get(&self, name: &str) -> Result<Option<SecretVec>, SecretBackendError> {
+        let backend = self.backend.kind();
+        if name == MASTER_KEY {
+            if let Some(reason) = self.rotation_pending.read().clone() {
+                return Err(SecretBackendError::RotationPending { backend, reason });
+            }
+        }
+        let secrets = self.secrets.read();
+        let Some(cached) = secrets.get(name) else { return Ok(None) };
+        if let Some(max) = self.max_staleness {
+            let age = cached.confirmed_at.elapsed();
+            if age > max {
+                return Err(SecretBackendError::Stale {
+                    name: name.to_string(),
+                    backend,
+                    age_secs: age.as_secs(),
+                    max_secs: max.as_secs(),
+                });
+            }
+        }
+        Ok(Some(cached.value.clone()))
+    }
+
+    /// 缓存中的主密钥，不看有效期（轮换时作为旧密钥）
+    pub(crate) fn cached_master_key(&self) -> Option<SecretVec> {
+        self.secrets.read().get(MASTER_KEY).map(|cached| cached.value.clone())
+    }
+
+    /// 重新读取所有密钥。未变的重新确认，变了的 API 密钥进入缓存（由
+    /// `ops::secret_refresh` 换进服务的 `ApiKeyRing`）；主密钥变了时保持旧值并
+    /// 返回新值，由调用方按轮换策略处理。读取失败时缓存不变，有效期继续计算。
+    pub async fn refresh(&self) -> Result<Option<SecretVec>, SecretBackendError> {
+        let mut fetched = Vec::with_capacity(SECRETS.len());
+        for (name, required) in SECRETS {
+            let value = self.backend.fetch(name).await?;
+            if value.is_none() && *required {
+                return Err(SecretBackendError::NotSet { name: name.to_string(), backend: self.backend.kind() });
+            }
+            fetched.push((*name, value));
+        }
+
+        let now = Instant::now();
+        let mut changed_master = None;
+        let mut secrets = self.secrets.write();
+        for (name, value) in fetched {
+            let Some(value) = value else {
+                secrets.remove(name);
+                continue;
+            };
+            match secrets.get_mut(name) {
+                Some(cached) if cached.value == value => {
+                    cached.confirmed_at = now;
+                    if name == MASTER_KEY {
+                        // 后端又回到了正在使用的密钥
+                        *self.rotation_pending.write() = None;
+                    }
+                }
+                Some(_) if name == MASTER_KEY => changed_master = Some(value),
+                Some(_) => {
+                    warn!("{} changed in the {} secret backend", name, self.backend.kind());
+                    secrets.insert(name, CachedSecret { value, confirmed_at: now });
+                }
+                None => {
+                    secrets.insert(name, CachedSecret { value, confirmed_at: now });
+                }
+            }
+        }
+        Ok(changed_master)
+    }
+
+    /// 缓存中的管理 API 密钥；后端没有 [`API_KEY`] 时为 `None`
+    pub fn api_keys(&self) -> Result<Option<ApiKeys>, SecretBackendError> {
+        let Some(primary) = self.get(API_KEY)? else { return Ok(None) };
+        Ok(Some(ApiKeys { primary, secondary: self.get(API_KEY_SECONDARY)? }))
+    }
+
+    /// 把轮换后的管理 API 密钥写入后端并更新缓存；其他实例在下一次刷新时读到
+    pub async fn store_api_keys(&self, keys: &ApiKeys) -> Result<(), SecretBackendError> {
+        let mut values = vec![(API_KEY, &keys.primary)];
+        values.extend(keys.secondary.as_ref().map(|secondary| (API_KEY_SECONDARY, secondary)));
+        self.backend.store(&values).await?;
+        let now = Instant::now();
+        let mut secrets = self.secrets.write();
+        for (name, value) in values {
+            secrets.insert(name, CachedSecret { value: value.clone(), confirmed_at: now });
+        }
+        Ok(())
+    }
+
+    /// 采用新主密钥（已完成重新封装）
+    pub fn adopt_master_key(&self, value: SecretVec) {
+        self.secrets.write().insert(MASTER_KEY, CachedSecret { value, confirmed_at: Instant::now() });
+        *self.rotation_pending.write() = None;
+    }
+
+    /// 拒绝新主密钥：在问题解决前主密钥不可用
+    pub fn refuse_master_key(&self, reason: String) {
+        *self.rotation_pending.write() = Some(reason);
+    }
+}
+
+lazy_static! {
+    /// 已安装的后端缓存；没有时直接读取环境变量
+    static ref INSTALLED: RwLock<Option<Arc<SecretStore>>> = RwLock::new(None);
+}
+
+/// 安装进程级密钥缓存，返回被替换的那个
+pub fn install(store: Arc<SecretStore>) -> Option<Arc<SecretStore>> {
+    INSTALLED.write().replace(store)
+}
+
+/// 回到环境变量
+pub fn uninstall() -> Option<Arc<SecretStore>> {
+    INSTALLED.write().take()
+}
+
+pub fn installed() -> Option<Arc<SecretStore>> {
+    INSTALLED.read().clone()
+}
+
+/// 主密钥的 base64 文本，与 `WALLET_ENC_KEY` 的格式相同
+pub fn master_key_encoded() -> Result<Zeroizing<String>, SecretBackendError> {
+    let Some(store) = installed() else {
+        return std::env::var(MASTER_KEY)
+            .map(Zeroizing::new)
+            .map_err(|_| SecretBackendError::NotSet { name: MASTER_KEY.to_string(), backend: "env" });
+    };
+    let backend = store.backend_kind();
+    let value = store.get(MASTER_KEY)?.ok_or(SecretBackendError::NotSet { name: MASTER_KEY.to_string(), backend })?;
+    String::from_utf8(value.to_vec())
+        .map(Zeroizing::new)
+        .map_err(|_| SecretBackendError::Misconfigured { backend, reason: format!("{} is not UTF-8", MASTER_KEY) })
+}
+
+/// 主密钥当前是否可用；未安装后端时不检查（与之前的行为一致）
+pub fn ensure_master_key() -> Result<(), SecretBackendError> {
+    match installed() {
+        Some(store) => store.get(MASTER_KEY).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+/// API 密钥；未设置时为 `None`
+pub fn api_key() -> Result<Option<SecretVec>, SecretBackendError> {
+    optional_secret(API_KEY)
+}
+
+/// 第二把 API 密钥；未设置时为 `None`
+pub fn api_key_secondary() -> Result<Option<SecretVec>, SecretBackendError> {
+    optional_secret(API_KEY_SECONDARY)
+}
+
+fn optional_secret(name: &str) -> Result<Option<SecretVec>, SecretBackendError> {
+    match installed() {
+        Some(store) => store.get(name),
+        None => Ok(std::env::var(name).ok().map(|key| vec_to_secret(key.into_bytes()))),
+    }
+}
@@ -7,13 +7,12 @@ use zeroize::Zeroizing;
 use crate::security::SecretVec;
 use base64::Engine;
 
-/// fetchwallet加密密钥
+/// fetchwallet加密密钥（已安装密钥后端时从后端缓存读取）
 pub fn get_wallet_enc_key() -> Result<SecretVec> {
-    let key_b64 = std::env::var("WALLET_ENC_KEY")
-        .map_err(|_| anyhow::anyhow!("WALLET_ENC_KEY not set"))?;
+    let key_b64 = super::secret_backend::master_key_encoded()?;
     
     let key_bytes = base64::engine::general_purpose::STANDARD
-        .decode(key_b64)
+        .decode(key_b64.as_bytes())
         .map_err(|e| anyhow::anyhow!("Invalid WALLET_ENC_KEY base64: {}", e))?;
     
     Ok(SecretVec::new(key_bytes))
@@ -45,12 +44,9 @@ pub fn get_bridge_mock_force_success() -> Result<String> {
         .map_err(|_| anyhow::anyhow!("BRIDGE_MOCK_FORCE_SUCCESS not set"))
 }
 
-/// fetch API 密钥
+/// fetch API 密钥（已安装密钥后端时从后端缓存读取）
 pub fn get_api_key() -> Result<SecretVec> {
-    let key = std::env::var("API_KEY")
-        .map_err(|_| anyhow::anyhow!("API_KEY not set"))?;
-    
-    Ok(SecretVec::new(key.into_bytes()))
+    super::secret_backend::api_key()?.ok_or_else(|| anyhow::anyhow!("API_KEY not set"))
 }
 
 /// check是否处于测试模式
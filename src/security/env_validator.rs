@@ -6,6 +6,8 @@ use anyhow::{Result, bail};
 use std::env;
 use tracing::{info, warn};
 
+use crate::security::env_manager::secret_backend;
+
 /// 环境变量validate器
 pub struct EnvValidator;
 
@@ -149,8 +151,8 @@ impl EnvValidator {
         
         let mut errors = Vec::new();
         
-        // 1. validateWALLET_ENC_KEY（必需）
-        match env::var("WALLET_ENC_KEY") {
+        // 1. validateWALLET_ENC_KEY（必需；已安装密钥后端时validate后端中的值）
+        match secret_backend::master_key_encoded() {
             Ok(key) => {
                 if let Err(e) = Self::validate_wallet_enc_key(&key) {
                     errors.push(format!("WALLET_ENC_KEY: {}", e));
@@ -163,11 +165,18 @@ impl EnvValidator {
         }
         
         // 2. validateAPI_KEY（可选但建议）
-        if let Ok(key) = env::var("API_KEY") {
+        if let Ok(Some(key)) = secret_backend::api_key() {
+            let key = zeroize::Zeroizing::new(String::from_utf8_lossy(&key).into_owned());
             if let Err(e) = Self::validate_api_key(&key) {
                 errors.push(format!("API_KEY: {}", e));
             }
         }
+        if let Ok(Some(key)) = secret_backend::api_key_secondary() {
+            let key = zeroize::Zeroizing::new(String::from_utf8_lossy(&key).into_owned());
+            if let Err(e) = Self::validate_api_key(&key) {
+                errors.push(format!("API_KEY_SECONDARY: {}", e));
+            }
+        }
         
         // 3. validateDATABASE_URL
         let db_url = env::var("DATABASE_URL")
@@ -0,0 +1,340 @@
+//! Signing-key usage accounting and rotation policy enforcement.
+//!
+//! Each signature is counted against the wallet's current signing-key
+//! version (label `wallet:<name>:signing` in the rotation tables). The
+//! policy check runs against in-memory counters; persistence goes through a
+//! background flusher so the signing path never waits on a DB write.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tracing::{info, warn};
+
+use crate::core::config::{KeyPolicyMode, KeyRotationPolicy};
+use crate::core::errors::WalletError;
+use crate::monitoring::{SecurityEvent, SecurityEventType, SecurityMonitor, SecuritySeverity, WalletMetrics};
+use crate::ops::incidents::spawn_named;
+use crate::storage::WalletStorage;
+
+/// How often queued usage increments are written to storage.
+pub const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Rotation-table label of a wallet's signing key.
+pub fn signing_key_label(wallet_name: &str) -> String {
+    format!("wallet:{}:signing", wallet_name)
+}
+
+/// `GET /api/wallets/:name/key_usage` payload.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct KeyUsageReport {
+    pub wallet: String,
+    pub version: i64,
+    pub usage_count: u64,
+    pub age_secs: u64,
+    /// `None` when no signature budget is configured.
+    pub remaining_signatures: Option<u64>,
+    /// `None` when no maximum key age is configured.
+    pub remaining_age_secs: Option<u64>,
+    pub rotation_required: bool,
+    pub policy_mode: KeyPolicyMode,
+}
+
+#[derive(Debug, Clone)]
+struct KeyState {
+    version: i64,
+    created_at: i64,
+    /// Persisted usage plus increments still queued for the flusher.
+    usage: u64,
+    /// Security event already raised for this version (warn mode).
+    warned: bool,
+}
+
+enum UsageCommand {
+    Increment { label: String, version: i64 },
+    Flush(oneshot::Sender<()>),
+}
+
+pub struct KeyUsageTracker {
+    storage: Arc<WalletStorage>,
+    policy: KeyRotationPolicy,
+    states: Mutex<HashMap<String, KeyState>>,
+    tx: mpsc::UnboundedSender<UsageCommand>,
+    metrics: Arc<WalletMetrics>,
+    security_monitor: Arc<SecurityMonitor>,
+}
+
+impl KeyUsageTracker {
+    /// Spawns the background flusher; must be called inside a Tokio runtime.
+    pub fn new(
+        storage: Arc<WalletStorage>,
+        policy: KeyRotationPolicy,
+        metrics: Arc<WalletMetrics>,
+        security_monitor: Arc<SecurityMonitor>,
+    ) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        spawn_named("key_usage_flusher", run_flusher(storage.clone(), rx, USAGE_FLUSH_INTERVAL));
+        Self { storage, policy, states: Mutex::new(HashMap::new()), tx, metrics, security_monitor }
+    }
+
+    pub fn policy(&self) -> &KeyRotationPolicy {
+        &self.policy
+    }
+
+    pub fn metrics(&self) -> &Arc<WalletMetrics> {
+        &self.metrics
+    }
+
+    pub fn security_monitor(&self) -> &Arc<SecurityMonitor> {
+        &self.security_monitor
+    }
+
+    /// Loads the current version of `label`, creating version 1 on first use.
+    async fn load_state(&self, label: &str) -> Result<KeyState, WalletError> {
+        let version = match self.storage.rotation_get_label(label).await? {
+            Some(l) => l.current_version,
+            None => {
+                let key_id = uuid::Uuid::new_v4().to_string();
+                self.storage.rotation_insert_version(label, 1, &key_id).await?;
+                self.storage.rotation_upsert_label(label, 1, Some(&key_id)).await?;
+                1
+            }
+        };
+
+        let record = match self.storage.rotation_get_version(label, version).await? {
+            Some(r) => r,
+            None => {
+                // label without a version row (pre-accounting data): seed it
+                let key_id = uuid::Uuid::new_v4().to_string();
+                self.storage.rotation_insert_version(label, version, &key_id).await?;
+                self.storage.rotation_get_version(label, version).await?.ok_or_else(|| {
+                    WalletError::StorageError(format!("key version missing for {}", label))
+                })?
+            }
+        };
+
+        Ok(KeyState {
+            version,
+            created_at: record.created_at,
+            usage: record.usage_count.max(0) as u64,
+            warned: false,
+        })
+    }
+
+    fn age_secs(state: &KeyState) -> u64 {
+        (chrono::Utc::now().timestamp() - state.created_at).max(0) as u64
+    }
+
+    fn is_overdue(&self, state: &KeyState) -> bool {
+        let over_budget = self.policy.max_signatures_per_key.is_some_and(|max| state.usage >= max);
+        let too_old = self.policy.max_key_age_secs.is_some_and(|max| Self::age_secs(state) >= max);
+        over_budget || too_old
+    }
+
+    /// Accounts one signature by `wallet_name`'s current key.
+    ///
+    /// Call right before signing. In block mode an overdue key yields
+    /// `WalletError::KeyRotationRequired` and nothing is counted.
+    pub async fn record_signature(&self, wallet_name: &str) -> Result<(), WalletError> {
+        let label = signing_key_label(wallet_name);
+        let mut states = self.states.lock().await;
+        if !states.contains_key(&label) {
+            let loaded = self.load_state(&label).await?;
+            states.insert(label.clone(), loaded);
+        }
+        let Some(state) = states.get_mut(&label) else {
+            return Err(WalletError::InternalError("key usage state missing".into()));
+        };
+
+        if self.is_overdue(state) {
+            match self.policy.policy_mode {
+                KeyPolicyMode::Block => {
+                    return Err(WalletError::KeyRotationRequired(format!(
+                        "signing key v{} of wallet {} exceeded its rotation policy",
+                        state.version, wallet_name
+                    )));
+                }
+                KeyPolicyMode::Warn => {
+                    self.metrics.record_key_rotation_overdue();
+                    if !state.warned {
+                        state.warned = true;
+                        self.security_monitor
+                            .report_security_event(SecurityEvent {
+                                event_type: SecurityEventType::KeyRotationOverdue,
+                                description: format!(
+                                    "signing key v{} of wallet {} is past its rotation policy ({} signatures, {}s old)",
+                                    state.version,
+                                    wallet_name,
+                                    state.usage,
+                                    Self::age_secs(state)
+                                ),
+                                severity: SecuritySeverity::Medium,
+                                timestamp: chrono::Utc::now(),
+                                source_ip: None,
+                                wallet_id: Some(wallet_name.to_string()),
+                            })
+                            .await;
+                    }
+                }
+            }
+        }
+
+        state.usage += 1;
+        let version = state.version;
+        drop(states);
+
+        if self.tx.send(UsageCommand::Increment { label, version }).is_err() {
+            warn!("key usage flusher stopped; usage for {} not persisted", wallet_name);
+        }
+        Ok(())
+    }
+
+    /// Current key version, usage and remaining budget for `wallet_name`.
+    pub async fn usage(&self, wallet_name: &str) -> Result<KeyUsageReport, WalletError> {
+        let label = signing_key_label(wallet_name);
+        let mut states = self.states.lock().await;
+        if !states.contains_key(&label) {
+            let loaded = self.load_state(&label).await?;
+            states.insert(label.clone(), loaded);
+        }
+        let Some(state) = states.get(&label) else {
+            return Err(WalletError::InternalError("key usage state missing".into()));
+        };
+
+        let age_secs = Self::age_secs(state);
+        Ok(KeyUsageReport {
+            wallet: wallet_name.to_string(),
+            version: state.version,
+            usage_count: state.usage,
+            age_secs,
+            remaining_signatures: self
+                .policy
+                .max_signatures_per_key
+                .map(|max| max.saturating_sub(state.usage)),
+            remaining_age_secs: self.policy.max_key_age_secs.map(|max| max.saturating_sub(age_secs)),
+            rotation_required: self.is_overdue(state),
+            policy_mode: self.policy.policy_mode,
+        })
+    }
+
+    /// Retires the current key version and starts a fresh one with zero usage.
+    /// Returns `(old_version, new_version)`.
+    pub async fn rotate(&self, wallet_name: &str) -> Result<(i64, i64), WalletError> {
+        self.flush().await;
+
+        let label = signing_key_label(wallet_name);
+        let mut states = self.states.lock().await;
+        let current = self.load_state(&label).await?;
+        let old_version = current.version;
+        let new_version = old_version + 1;
+
+        let key_id = uuid::Uuid::new_v4().to_string();
+        self.storage.rotation_insert_version(&label, new_version, &key_id).await?;
+        self.storage.rotation_upsert_label(&label, new_version, Some(&key_id)).await?;
+        self.storage.rotation_mark_retired(&label, old_version).await?;
+
+        let fresh = self.load_state(&label).await?;
+        states.insert(label, fresh);
+        info!("signing key for {} rotated: v{} -> v{}", wallet_name, old_version, new_version);
+        Ok((old_version, new_version))
+    }
+
+    /// Writes all queued increments and waits for completion.
+    pub async fn flush(&self) {
+        let (ack, done) = oneshot::channel();
+        if self.tx.send(UsageCommand::Flush(ack)).is_ok() {
+            let _ = done.await;
+        }
+    }
+
+    /// Flushes outstanding usage; call before the process exits.
+    pub async fn shutdown(&self) {
+        self.flush().await;
+    }
+}
+
+async fn flush_pending(storage: &WalletStorage, pending: &mut HashMap<(String, i64), i64>) {
+    let batch: Vec<_> = pending.drain().collect();
+    for ((label, version), count) in batch {
+        if let Err(e) = storage.rotation_inc_usage_by(&label, version, count).await {
+            warn!("key usage flush failed for {} v{}: {}", label, version, e);
+            // keep the counts for the next tick
+            *pending.entry((label, version)).or_insert(0) += count;
+        }
+    }
+}
+
+async fn run_flusher(
+    storage: Arc<WalletStorage>,
+    mut rx: mpsc::UnboundedReceiver<UsageCommand>,
+    interval: Duration,
+) {
+    let mut pending: HashMap<(String, i64), i64> = HashMap::new();
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            cmd = rx.recv() => match cmd {
+                Some(UsageCommand::Increment { label, version }) => {
+                    *pending.entry((label, version)).or_insert(0) += 1;
+                }
+                Some(UsageCommand::Flush(ack)) => {
+                    flush_pending(&storage, &mut pending).await;
+                    let _ = ack.send(());
+                }
+                None => {
+                    // tracker dropped: persist what is left and stop
+                    flush_pending(&storage, &mut pending).await;
+                    break;
+                }
+            },
+            _ = ticker.tick() => {
+                if !pending.is_empty() {
+                    flush_pending(&storage, &mut pending).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn tracker(policy: KeyRotationPolicy) -> KeyUsageTracker {
+        let storage = Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap());
+        let metrics = Arc::new(WalletMetrics::new().unwrap());
+        let monitor = Arc::new(SecurityMonitor::new(metrics.clone()));
+        KeyUsageTracker::new(storage, policy, metrics, monitor)
+    }
+
+    #[tokio::test]
+    async fn test_usage_report_tracks_budget() {
+        let t = tracker(KeyRotationPolicy {
+            max_signatures_per_key: Some(10),
+            ..Default::default()
+        })
+        .await;
+        for _ in 0..4 {
+            t.record_signature("alice").await.unwrap();
+        }
+        let report = t.usage("alice").await.unwrap();
+        assert_eq!(report.version, 1);
+        assert_eq!(report.usage_count, 4);
+        assert_eq!(report.remaining_signatures, Some(6));
+        assert_eq!(report.remaining_age_secs, None);
+        assert!(!report.rotation_required);
+    }
+
+    #[tokio::test]
+    async fn test_flush_persists_batched_usage() {
+        let t = tracker(KeyRotationPolicy::default()).await;
+        for _ in 0..3 {
+            t.record_signature("bob").await.unwrap();
+        }
+        t.flush().await;
+        let v = t.storage.rotation_get_version(&signing_key_label("bob"), 1).await.unwrap().unwrap();
+        assert_eq!(v.usage_count, 3);
+    }
+}
@@ -4,6 +4,7 @@
 use crate::core::memory_protection::{lock_memory, unlock_memory};
 
 use crate::core::errors::WalletError;
+use crate::security::SecretVec;
 use std::alloc::{alloc, dealloc, Layout};
 use std::ptr;
 
@@ -236,6 +237,180 @@ where
     }
 }
 
+/// 从密钥源交出的秘密字节（wallet主密钥、导入的 keystore 私钥等）
+///
+/// 借用（`Deref<Target = [u8]>`）和 [`TaintedBytes::to_secret`] 得到的副本都会清零；
+/// 复制到普通 `Vec<u8>` 的出口（`to_vec` / `extend_into`）在测试构建中会被记录，
+/// 测试结尾调用 [`assert_no_secret_leaks`] 检查。release 构建中它只是
+/// [`SecretVec`] 的透明包装，不做任何记录。
+#[repr(transparent)]
+pub struct TaintedBytes {
+    inner: TaintedInner,
+}
+
+#[cfg(not(any(test, feature = "test-env")))]
+type TaintedInner = SecretVec;
+
+#[cfg(any(test, feature = "test-env"))]
+#[derive(Clone)]
+struct TaintedInner {
+    bytes: SecretVec,
+    label: &'static str,
+}
+
+impl TaintedBytes {
+    /// `label` 出现在泄漏报告里，说明秘密来自哪里
+    #[cfg(not(any(test, feature = "test-env")))]
+    #[inline]
+    pub fn new(bytes: SecretVec, _label: &'static str) -> Self {
+        Self { inner: bytes }
+    }
+
+    #[cfg(any(test, feature = "test-env"))]
+    pub fn new(bytes: SecretVec, label: &'static str) -> Self {
+        Self { inner: TaintedInner { bytes, label } }
+    }
+
+    /// 借用已有的 `bytes`，不复制。只在 release 构建中提供：测试构建需要自己的
+    /// 副本来带上 `label`
+    #[cfg(not(any(test, feature = "test-env")))]
+    #[inline]
+    pub fn borrowed(bytes: &SecretVec) -> &Self {
+        // SAFETY: `TaintedBytes` 是 `#[repr(transparent)]`，release 构建中唯一的字段就是 `SecretVec`
+        unsafe { &*(bytes as *const SecretVec as *const Self) }
+    }
+
+    #[cfg(not(any(test, feature = "test-env")))]
+    #[inline]
+    fn bytes(&self) -> &SecretVec {
+        &self.inner
+    }
+
+    #[cfg(any(test, feature = "test-env"))]
+    fn bytes(&self) -> &SecretVec {
+        &self.inner.bytes
+    }
+
+    /// 复制到不会清零的 `Vec<u8>`：测试构建中记为一次泄漏
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.record("to_vec", None);
+        self.bytes().to_vec()
+    }
+
+    /// 同 [`TaintedBytes::to_vec`]，但调用方已确认副本会被就地覆盖或清零（白名单）
+    pub fn to_vec_allowed(&self, reason: &'static str) -> Vec<u8> {
+        self.record("to_vec", Some(reason));
+        self.bytes().to_vec()
+    }
+
+    /// 追加到调用方的缓冲区：测试构建中记为一次泄漏
+    pub fn extend_into(&self, dest: &mut Vec<u8>) {
+        self.record("extend", None);
+        dest.extend_from_slice(self.bytes());
+    }
+
+    /// 会清零的副本，不算泄漏
+    pub fn to_secret(&self) -> SecretVec {
+        SecretVec::new(self.bytes().to_vec())
+    }
+
+    #[cfg(not(any(test, feature = "test-env")))]
+    #[inline(always)]
+    fn record(&self, _operation: &'static str, _allowed: Option<&'static str>) {}
+
+    #[cfg(any(test, feature = "test-env"))]
+    fn record(&self, operation: &'static str, allowed: Option<&'static str>) {
+        leak_audit::record(SecretEscape {
+            label: self.inner.label,
+            operation,
+            len: self.inner.bytes.len(),
+            allowed,
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        });
+    }
+}
+
+impl std::ops::Deref for TaintedBytes {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.bytes()
+    }
+}
+
+/// 克隆仍是 `TaintedBytes`（清零且继续受追踪），不算泄漏
+impl Clone for TaintedBytes {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+impl From<SecretVec> for TaintedBytes {
+    fn from(bytes: SecretVec) -> Self {
+        Self::new(bytes, "SecretVec")
+    }
+}
+
+impl std::fmt::Debug for TaintedBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TaintedBytes([REDACTED; {}])", self.bytes().len())
+    }
+}
+
+/// 一次秘密字节复制到非清零容器的记录
+#[cfg(any(test, feature = "test-env"))]
+#[derive(Debug, Clone)]
+pub struct SecretEscape {
+    pub label: &'static str,
+    pub operation: &'static str,
+    pub len: usize,
+    /// 白名单理由；`None` 表示未经许可的泄漏
+    pub allowed: Option<&'static str>,
+    pub backtrace: String,
+}
+
+#[cfg(any(test, feature = "test-env"))]
+pub use leak_audit::{assert_no_secret_leaks, take_secret_escapes};
+
+/// 线程局部的泄漏登记表。`#[tokio::test]` 默认单线程运行时，整个被测流程
+/// 都记在测试线程上；多线程运行时下只能看到当前线程的记录。
+#[cfg(any(test, feature = "test-env"))]
+mod leak_audit {
+    use super::SecretEscape;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static ESCAPES: RefCell<Vec<SecretEscape>> = const { RefCell::new(Vec::new()) };
+    }
+
+    pub(super) fn record(escape: SecretEscape) {
+        ESCAPES.with(|escapes| escapes.borrow_mut().push(escape));
+    }
+
+    /// 取出并清空当前线程的记录（含白名单项）
+    pub fn take_secret_escapes() -> Vec<SecretEscape> {
+        ESCAPES.with(|escapes| std::mem::take(&mut *escapes.borrow_mut()))
+    }
+
+    /// 清空记录；存在未列入白名单的泄漏时 panic，并附上每次泄漏的调用栈
+    #[track_caller]
+    pub fn assert_no_secret_leaks() {
+        let leaks: Vec<SecretEscape> =
+            take_secret_escapes().into_iter().filter(|escape| escape.allowed.is_none()).collect();
+        if leaks.is_empty() {
+            return;
+        }
+        let report: Vec<String> = leaks
+            .iter()
+            .map(|leak| {
+                format!("{} ({} bytes) escaped via {}:\n{}", leak.label, leak.len, leak.operation, leak.backtrace)
+            })
+            .collect();
+        panic!("{} secret escape(s) into non-zeroizing memory:\n{}", leaks.len(), report.join("\n"));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
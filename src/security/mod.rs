@@ -5,9 +5,12 @@
 //! zeroization utilities, and other protective measures.
 
 pub mod access_control;
+pub mod approval_key;
 pub mod compliance;
+pub mod delegation_key;
 pub mod encryption;
 pub mod env_manager;
+pub mod key_usage;
 pub mod memory_protection;
 pub mod mnemonic_export;
 pub mod password_validator;
@@ -15,13 +18,15 @@ pub mod env_validator;
 pub mod error_sanitizer;
 pub mod secret;
 pub mod shamir;
+pub mod signing_secret;
+pub mod sweep_capability;
 
 // Add the new anti-debug module
 pub mod anti_debug;
 
 // Re-export commonly used security functions for convenience
 pub use anti_debug::is_debugger_present;
-pub use env_manager::{PermissionLevel, SecureEnvManager, SECURE_ENV_MANAGER};
+pub use env_manager::{ApiKeys, PermissionLevel, SecureEnvManager, SECURE_ENV_MANAGER};
 
 // Secret buffer alias re-export
 pub use secret::SecretVec;
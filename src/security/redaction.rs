@@ -1,4 +1,6 @@
 // Simple helpers to avoid accidental printing of secrets in logs/tests.
+use once_cell::sync::Lazy;
+use regex::Regex;
 use std::env;
 
 /// Redact a text body unless DEV_PRINT_SECRETS=1 is set in the environment.
@@ -18,3 +20,67 @@ pub fn redact_hex_bytes(bytes: &[u8]) -> String {
     }
     format!("<redacted hex len={}>", bytes.len())
 }
+
+/// Field names (lowercased substrings) whose values never leave the process
+const SENSITIVE_FIELDS: &[&str] =
+    &["private", "secret", "mnemonic", "seed", "password", "passphrase", "encrypted", "key_material"];
+
+/// Whether a field named `name` would be redacted by [`redact_json`]
+pub fn is_sensitive_field(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    SENSITIVE_FIELDS.iter().any(|s| lower.contains(s))
+}
+
+/// Raw key material: 32-byte hex scalars, extended private keys, JWTs and PEM blocks
+static SECRET_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    vec![
+        Regex::new(r"\b(?:0x)?[0-9a-fA-F]{64}\b").unwrap(),
+        Regex::new(r"\b[xyzt]prv[1-9A-HJ-NP-Za-km-z]{100,}").unwrap(),
+        Regex::new(r"eyJ[a-zA-Z0-9_-]*\.eyJ[a-zA-Z0-9_-]*\.[a-zA-Z0-9_-]*").unwrap(),
+        Regex::new(r"-----BEGIN [A-Z ]+-----").unwrap(),
+    ]
+});
+
+/// Shortest run of BIP39 words treated as a seed phrase
+const MIN_MNEMONIC_WORDS: usize = 12;
+
+/// Whether free text looks like it carries a secret: key material matching
+/// one of the patterns above or twelve consecutive BIP39 English words. Used to
+/// refuse user-supplied text that would otherwise be stored and echoed in
+/// the clear.
+pub fn contains_secret(text: &str) -> bool {
+    if SECRET_PATTERNS.iter().any(|re| re.is_match(text)) {
+        return true;
+    }
+    let mut run = 0;
+    for word in text.split(|c: char| !c.is_ascii_alphabetic()).filter(|w| !w.is_empty()) {
+        if bip39::Language::English.find_word(&word.to_ascii_lowercase()).is_some() {
+            run += 1;
+            if run >= MIN_MNEMONIC_WORDS {
+                return true;
+            }
+        } else {
+            run = 0;
+        }
+    }
+    false
+}
+
+/// Replace the values of sensitive-looking fields in a JSON document with
+/// `"[REDACTED]"`, recursively. Used for payloads persisted or sent to
+/// external consumers; unlike [`redact_body`] it ignores DEV_PRINT_SECRETS.
+pub fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                if is_sensitive_field(k) {
+                    *v = serde_json::Value::String("[REDACTED]".to_string());
+                } else {
+                    redact_json(v);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
@@ -0,0 +1,95 @@
+//! HMAC signing secret of an API key, sealed at rest.
+//!
+//! Bearer keys are stored as a SHA-256 hash, but a signing key's secret has
+//! to be recovered to check each request's signature. It is sealed the same
+//! way as the dead-man's [`super::sweep_capability`]: HKDF-SHA256 derives a
+//! one-off AES-256-GCM key from the process KEK and a random salt, so a
+//! database copy (a backup, a sandbox source) does not carry usable secrets.
+//! The key id is bound as associated data so a sealed secret cannot be moved
+//! to another key. Envelope rotation does not reseal these secrets; keys
+//! issued under a retired KEK stop verifying and have to be reissued.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use hkdf::Hkdf;
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::core::errors::WalletError;
+use crate::core::wallet::create::load_envelope_kek;
+
+const HKDF_INFO: &[u8] = b"api-key-signing-secret/v1";
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct SealedSigningSecret {
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+impl std::fmt::Debug for SealedSigningSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SealedSigningSecret").finish_non_exhaustive()
+    }
+}
+
+fn aad(key_id: &str) -> Vec<u8> {
+    format!("api-key/v1\n{}", key_id).into_bytes()
+}
+
+fn cipher(salt: &[u8]) -> Result<Aes256Gcm, WalletError> {
+    let master = Zeroizing::new(load_envelope_kek()?);
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(salt), &*master)
+        .expand(HKDF_INFO, &mut *key)
+        .map_err(|_| WalletError::CryptoError("Failed to derive signing secret key".to_string()))?;
+    Aes256Gcm::new_from_slice(&*key).map_err(|e| WalletError::CryptoError(format!("Failed to create cipher: {}", e)))
+}
+
+impl SealedSigningSecret {
+    /// Seals `secret` for the API key `key_id`.
+    pub fn seal(key_id: &str, secret: &str) -> Result<Self, WalletError> {
+        let mut salt = vec![0u8; SALT_LEN];
+        let mut nonce = vec![0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let ciphertext = cipher(&salt)?
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: secret.as_bytes(), aad: &aad(key_id) })
+            .map_err(|_| WalletError::CryptoError("Failed to seal signing secret".to_string()))?;
+        Ok(Self { salt, nonce, ciphertext })
+    }
+
+    pub fn open(&self, key_id: &str) -> Result<Zeroizing<String>, WalletError> {
+        if self.nonce.len() != NONCE_LEN {
+            return Err(WalletError::CryptoError("Corrupt signing secret".to_string()));
+        }
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let secret = cipher(&self.salt)?
+            .decrypt(Nonce::from_slice(&self.nonce), Payload { msg: &self.ciphertext, aad: &aad(key_id) })
+            .map_err(|_| WalletError::SecurityError("Signing secret does not open for this key".to_string()))?;
+        String::from_utf8(secret)
+            .map(Zeroizing::new)
+            .map_err(|_| WalletError::CryptoError("Corrupt signing secret".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_opens_only_for_its_key() {
+        std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+        let sealed = SealedSigningSecret::seal("key-1", "wsk_secret").unwrap();
+        assert!(!sealed.ciphertext.windows(10).any(|w| w == b"wsk_secret"));
+
+        assert_eq!(sealed.open("key-1").unwrap().as_str(), "wsk_secret");
+        assert!(matches!(sealed.open("key-2"), Err(WalletError::SecurityError(_))));
+    }
+}
@@ -0,0 +1,158 @@
+//! Pre-authorized sweep capability for the dead-man's switch.
+//!
+//! Arming the switch is the last moment the owner's password is available,
+//! so the wallet's signing key is sealed then: HKDF-SHA256 derives a one-off
+//! key from the process KEK (`WALLET_ENC_KEY`) and a random salt, and
+//! AES-256-GCM encrypts the signing key with associated data naming the
+//! beneficiary address and the network set. The sealed key can only be
+//! opened by presenting that exact destination; any other address, or a
+//! policy row whose beneficiary was edited in the database, fails
+//! authentication before a byte of key material is released. The opened
+//! [`SweepSigner`] in turn only builds transfers to the destination it was
+//! opened for.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use ethers::core::k256::ecdsa::SigningKey;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, TransactionRequest, U256};
+use hkdf::Hkdf;
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::core::errors::WalletError;
+use crate::core::wallet::create::load_envelope_kek;
+
+const HKDF_INFO: &[u8] = b"deadman-sweep-capability/v1";
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Signing key sealed for one beneficiary and network set
+#[derive(Clone)]
+pub struct SweepCapability {
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+impl std::fmt::Debug for SweepCapability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SweepCapability").finish_non_exhaustive()
+    }
+}
+
+/// Associated data: the destination and the (order-independent) network set
+fn binding(destination: &str, networks: &[String]) -> Vec<u8> {
+    let mut networks = networks.to_vec();
+    networks.sort();
+    networks.dedup();
+    format!("deadman-sweep/v1\n{}\n{}", destination.to_ascii_lowercase(), networks.join(",")).into_bytes()
+}
+
+fn cipher(salt: &[u8]) -> Result<Aes256Gcm, WalletError> {
+    let master = Zeroizing::new(load_envelope_kek()?);
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(salt), &*master)
+        .expand(HKDF_INFO, &mut *key)
+        .map_err(|_| WalletError::CryptoError("Failed to derive sweep capability key".to_string()))?;
+    Aes256Gcm::new_from_slice(&*key).map_err(|e| WalletError::CryptoError(format!("Failed to create cipher: {}", e)))
+}
+
+impl SweepCapability {
+    /// Seals `secret_key` (32-byte secp256k1 scalar) for `beneficiary` on `networks`.
+    pub fn seal(beneficiary: &str, networks: &[String], secret_key: &[u8]) -> Result<Self, WalletError> {
+        let mut salt = vec![0u8; SALT_LEN];
+        let mut nonce = vec![0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+        let aad = binding(beneficiary, networks);
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let ciphertext = cipher(&salt)?
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: secret_key, aad: &aad })
+            .map_err(|_| WalletError::CryptoError("Failed to seal sweep capability".to_string()))?;
+        Ok(Self { salt, nonce, ciphertext })
+    }
+
+    /// Opens the capability for a transfer to `destination`. Fails unless
+    /// `destination` and `networks` are exactly what the key was sealed for.
+    pub fn open(&self, destination: &str, networks: &[String]) -> Result<SweepSigner, WalletError> {
+        let to: Address = destination
+            .parse()
+            .map_err(|_| WalletError::ValidationError(format!("Invalid sweep destination: {}", destination)))?;
+        if self.nonce.len() != NONCE_LEN {
+            return Err(WalletError::CryptoError("Corrupt sweep capability".to_string()));
+        }
+        let aad = binding(destination, networks);
+        #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+        let secret = Zeroizing::new(
+            cipher(&self.salt)?
+                .decrypt(Nonce::from_slice(&self.nonce), Payload { msg: &self.ciphertext, aad: &aad })
+                .map_err(|_| {
+                    WalletError::SecurityError(
+                        "Sweep capability is not valid for this destination".to_string(),
+                    )
+                })?,
+        );
+        let key = SigningKey::from_slice(&secret)
+            .map_err(|e| WalletError::CryptoError(format!("Invalid sealed key: {}", e)))?;
+        Ok(SweepSigner { signer: LocalWallet::from(key), to })
+    }
+}
+
+/// An opened capability: signs transfers to one fixed destination only
+pub struct SweepSigner {
+    signer: LocalWallet,
+    to: Address,
+}
+
+impl SweepSigner {
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    pub fn destination(&self) -> Address {
+        self.to
+    }
+
+    pub fn signer(&self) -> &LocalWallet {
+        &self.signer
+    }
+
+    /// Native transfer of `value` to the bound destination
+    pub fn transfer(&self, value: U256) -> TypedTransaction {
+        TransactionRequest::new().to(self.to).value(value).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BENEFICIARY: &str = "0x00000000000000000000000000000000000000B0";
+
+    fn networks() -> Vec<String> {
+        vec!["polygon".to_string(), "eth".to_string()]
+    }
+
+    #[test]
+    fn test_capability_opens_only_for_sealed_destination() {
+        std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+        let key = [7u8; 32];
+        let capability = SweepCapability::seal(BENEFICIARY, &networks(), &key).unwrap();
+
+        // 大小写与network顺序不影响绑定
+        let signer = capability
+            .open(&BENEFICIARY.to_lowercase(), &["eth".to_string(), "polygon".to_string()])
+            .unwrap();
+        assert_eq!(signer.address(), LocalWallet::from(SigningKey::from_slice(&key).unwrap()).address());
+        assert_eq!(signer.transfer(U256::one()).to_addr(), Some(&BENEFICIARY.parse::<Address>().unwrap()));
+
+        let other = "0x00000000000000000000000000000000000000C0";
+        assert!(matches!(capability.open(other, &networks()), Err(WalletError::SecurityError(_))));
+        assert!(matches!(capability.open(BENEFICIARY, &["eth".to_string()]), Err(WalletError::SecurityError(_))));
+    }
+}
@@ -0,0 +1,101 @@
+//! Per-wallet address book.
+//!
+//! One row per (wallet, network, address); addresses are stored in the
+//! normalized form produced at import, so the primary key also deduplicates
+//! differently-cased spellings of one EVM address. Tags are kept as a single
+//! `;`-joined column in sorted order.
+
+use anyhow::Result;
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+use crate::core::address_book::AddressBookEntry;
+
+#[derive(FromRow)]
+struct EntryRow {
+    network: String,
+    address: String,
+    label: String,
+    tags: String,
+}
+
+impl From<EntryRow> for AddressBookEntry {
+    fn from(row: EntryRow) -> Self {
+        let tags = row.tags.split(';').filter(|tag| !tag.is_empty()).map(str::to_string).collect();
+        AddressBookEntry { network: row.network, address: row.address, label: row.label, tags }
+    }
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS address_book (
+            wallet_name TEXT NOT NULL,
+            network TEXT NOT NULL,
+            address TEXT NOT NULL,
+            label TEXT NOT NULL,
+            tags TEXT NOT NULL DEFAULT '',
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (wallet_name, network, address)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Ordered by network, then address
+pub async fn list(pool: &SqlitePool, wallet_name: &str) -> Result<Vec<AddressBookEntry>> {
+    let rows = sqlx::query_as::<_, EntryRow>(
+        "SELECT network, address, label, tags FROM address_book WHERE wallet_name = ?1 ORDER BY network, address",
+    )
+    .bind(wallet_name)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to list address book: {}", e))?;
+    Ok(rows.into_iter().map(Into::into).collect())
+}
+
+/// Inserts or overwrites the label and tags of each entry
+pub async fn upsert(
+    conn: &mut SqliteConnection,
+    wallet_name: &str,
+    entries: &[AddressBookEntry],
+    now: i64,
+) -> Result<()> {
+    for entry in entries {
+        sqlx::query(
+            r#"
+            INSERT INTO address_book (wallet_name, network, address, label, tags, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+            ON CONFLICT (wallet_name, network, address)
+            DO UPDATE SET label = excluded.label, tags = excluded.tags, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(wallet_name)
+        .bind(&entry.network)
+        .bind(&entry.address)
+        .bind(&entry.label)
+        .bind(entry.tags.join(";"))
+        .bind(now)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to write address book entry: {}", e))?;
+    }
+    Ok(())
+}
+
+pub async fn delete_for_wallet(conn: &mut SqliteConnection, wallet_name: &str) -> Result<()> {
+    sqlx::query("DELETE FROM address_book WHERE wallet_name = ?1").bind(wallet_name).execute(&mut *conn).await?;
+    Ok(())
+}
+
+pub async fn rename(conn: &mut SqliteConnection, wallet_name: &str, new_name: &str) -> Result<()> {
+    sqlx::query("UPDATE address_book SET wallet_name = ?1 WHERE wallet_name = ?2")
+        .bind(new_name)
+        .bind(wallet_name)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,343 @@
+//! Four-eyes approval queue for sends above a wallet's review threshold.
+//!
+//! A held send is stored without any signature: only what is needed to build
+//! the same unsigned transaction again once a second operator approves it,
+//! and in `approval_keys` the requester's signing key sealed for exactly that
+//! transaction ([`ApprovalKey`]). The key row goes away with the move out of
+//! `pending`, whatever the outcome.
+//! Status moves `pending` -> `approved` | `rejected` | `expired`, and
+//! `approved` -> `failed` if the approved send could not be broadcast. Every
+//! move is a compare-and-set on the current status, so two approvers racing
+//! on the same row cannot both execute it.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow, Row, SqliteConnection};
+
+use crate::security::approval_key::ApprovalKey;
+
+pub const APPROVAL_PENDING: &str = "pending";
+pub const APPROVAL_APPROVED: &str = "approved";
+pub const APPROVAL_REJECTED: &str = "rejected";
+pub const APPROVAL_EXPIRED: &str = "expired";
+pub const APPROVAL_FAILED: &str = "failed";
+
+/// The held send: the unsigned transaction minus nonce and gas, which are
+/// filled in when it is finally signed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalPayload {
+    /// Lowercase `0x` hex
+    pub from: String,
+    pub to: String,
+    /// Wei (decimal)
+    pub value: String,
+    /// ERC-20 contract; `None` for native transfers
+    pub token: Option<String>,
+    pub allow_duplicate: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApprovalRecord {
+    pub id: String,
+    pub wallet_name: String,
+    pub network: String,
+    pub payload: ApprovalPayload,
+    /// Decimal whole units, as requested
+    pub amount: String,
+    /// User id of the requester, or `admin`
+    pub requested_by: String,
+    /// Wallet-scoped token the request came through
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<String>,
+    pub status: String,
+    pub decided_by: Option<String>,
+    /// Rejection reason, or why an approved send failed
+    pub reason: Option<String>,
+    pub tx_hash: Option<String>,
+    /// Unix seconds
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub decided_at: Option<i64>,
+}
+
+#[derive(FromRow)]
+struct ApprovalRow {
+    id: String,
+    wallet_name: String,
+    network: String,
+    payload: String,
+    amount: String,
+    requested_by: String,
+    token_id: Option<String>,
+    status: String,
+    decided_by: Option<String>,
+    reason: Option<String>,
+    tx_hash: Option<String>,
+    created_at: i64,
+    expires_at: i64,
+    decided_at: Option<i64>,
+}
+
+impl TryFrom<ApprovalRow> for ApprovalRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ApprovalRow) -> Result<Self> {
+        Ok(Self {
+            payload: serde_json::from_str(&row.payload)?,
+            id: row.id,
+            wallet_name: row.wallet_name,
+            network: row.network,
+            amount: row.amount,
+            requested_by: row.requested_by,
+            token_id: row.token_id,
+            status: row.status,
+            decided_by: row.decided_by,
+            reason: row.reason,
+            tx_hash: row.tx_hash,
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            decided_at: row.decided_at,
+        })
+    }
+}
+
+/// Fields set by a status change
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalDecision<'a> {
+    pub decided_by: Option<&'a str>,
+    pub reason: Option<&'a str>,
+    pub tx_hash: Option<&'a str>,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS pending_approvals (
+            id TEXT PRIMARY KEY,
+            wallet_name TEXT NOT NULL,
+            network TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            amount TEXT NOT NULL,
+            requested_by TEXT NOT NULL,
+            token_id TEXT,
+            status TEXT NOT NULL,
+            decided_by TEXT,
+            reason TEXT,
+            tx_hash TEXT,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
+            decided_at INTEGER
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_pending_approvals_status ON pending_approvals (status, created_at)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS approval_keys (
+            approval_id TEXT PRIMARY KEY,
+            key_salt BLOB NOT NULL,
+            key_nonce BLOB NOT NULL,
+            key_ciphertext BLOB NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS review_thresholds (
+            wallet_name TEXT PRIMARY KEY,
+            threshold TEXT NOT NULL,
+            updated_by TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+const COLUMNS: &str = "id, wallet_name, network, payload, amount, requested_by, token_id, status, \
+                       decided_by, reason, tx_hash, created_at, expires_at, decided_at";
+
+pub async fn insert(conn: &mut SqliteConnection, record: &ApprovalRecord) -> Result<()> {
+    sqlx::query(&format!(
+        "INSERT INTO pending_approvals ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        COLUMNS
+    ))
+    .bind(&record.id)
+    .bind(&record.wallet_name)
+    .bind(&record.network)
+    .bind(serde_json::to_string(&record.payload)?)
+    .bind(&record.amount)
+    .bind(&record.requested_by)
+    .bind(&record.token_id)
+    .bind(&record.status)
+    .bind(&record.decided_by)
+    .bind(&record.reason)
+    .bind(&record.tx_hash)
+    .bind(record.created_at)
+    .bind(record.expires_at)
+    .bind(record.decided_at)
+    .execute(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store approval request: {}", e))?;
+    Ok(())
+}
+
+pub async fn insert_key(conn: &mut SqliteConnection, id: &str, key: &ApprovalKey) -> Result<()> {
+    sqlx::query("INSERT INTO approval_keys (approval_id, key_salt, key_nonce, key_ciphertext) VALUES (?1, ?2, ?3, ?4)")
+        .bind(id)
+        .bind(&key.salt)
+        .bind(&key.nonce)
+        .bind(&key.ciphertext)
+        .execute(conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to store approval key: {}", e))?;
+    Ok(())
+}
+
+pub async fn get_key(pool: &SqlitePool, id: &str) -> Result<Option<ApprovalKey>> {
+    let row = sqlx::query("SELECT key_salt, key_nonce, key_ciphertext FROM approval_keys WHERE approval_id = ?1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(|row| ApprovalKey {
+        salt: row.get("key_salt"),
+        nonce: row.get("key_nonce"),
+        ciphertext: row.get("key_ciphertext"),
+    }))
+}
+
+pub async fn delete_key(conn: &mut SqliteConnection, id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM approval_keys WHERE approval_id = ?1")
+        .bind(id)
+        .execute(conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to delete approval key: {}", e))?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, id: &str) -> Result<Option<ApprovalRecord>> {
+    let sql = format!("SELECT {} FROM pending_approvals WHERE id = ?1", COLUMNS);
+    sqlx::query_as::<_, ApprovalRow>(&sql).bind(id).fetch_optional(pool).await?.map(TryInto::try_into).transpose()
+}
+
+/// Newest first; `status` filters when set.
+pub async fn list(pool: &SqlitePool, status: Option<&str>, limit: i64) -> Result<Vec<ApprovalRecord>> {
+    let sql = format!(
+        "SELECT {} FROM pending_approvals WHERE (?1 IS NULL OR status = ?1) ORDER BY created_at DESC, id LIMIT ?2",
+        COLUMNS
+    );
+    let rows = sqlx::query_as::<_, ApprovalRow>(&sql).bind(status).bind(limit).fetch_all(pool).await?;
+    rows.into_iter().map(TryInto::try_into).collect()
+}
+
+/// Ids of `pending` rows whose `expires_at` has passed.
+pub async fn due_for_expiry(pool: &SqlitePool, now: i64) -> Result<Vec<String>> {
+    let rows = sqlx::query("SELECT id FROM pending_approvals WHERE status = ?1 AND expires_at <= ?2")
+        .bind(APPROVAL_PENDING)
+        .bind(now)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(|r| r.get("id")).collect())
+}
+
+/// Moves `id` from `from` to `to`; `false` if it was not in `from`. Leaving
+/// `pending` for anything but `expired` also requires the row not to have
+/// expired yet.
+pub async fn transition(
+    conn: &mut SqliteConnection,
+    id: &str,
+    from: &str,
+    to: &str,
+    decision: &ApprovalDecision<'_>,
+    now: i64,
+) -> Result<bool> {
+    let require_live = from == APPROVAL_PENDING && to != APPROVAL_EXPIRED;
+    let result = sqlx::query(
+        r#"
+        UPDATE pending_approvals
+        SET status = ?3,
+            decided_by = COALESCE(?4, decided_by),
+            reason = COALESCE(?5, reason),
+            tx_hash = COALESCE(?6, tx_hash),
+            decided_at = COALESCE(decided_at, ?7)
+        WHERE id = ?1 AND status = ?2 AND (?8 = 0 OR expires_at > ?7)
+        "#,
+    )
+    .bind(id)
+    .bind(from)
+    .bind(to)
+    .bind(decision.decided_by)
+    .bind(decision.reason)
+    .bind(decision.tx_hash)
+    .bind(now)
+    .bind(require_live)
+    .execute(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to update approval: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Sets `tx_hash` on an approved row once its send went out.
+pub async fn set_tx_hash(pool: &SqlitePool, id: &str, tx_hash: &str) -> Result<()> {
+    sqlx::query("UPDATE pending_approvals SET tx_hash = ?2 WHERE id = ?1")
+        .bind(id)
+        .bind(tx_hash)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update approval: {}", e))?;
+    Ok(())
+}
+
+pub async fn get_threshold(pool: &SqlitePool, wallet_name: &str) -> Result<Option<String>> {
+    let row = sqlx::query("SELECT threshold FROM review_thresholds WHERE wallet_name = ?1")
+        .bind(wallet_name)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(|r| r.get("threshold")))
+}
+
+/// `None` removes the threshold (no review).
+pub async fn put_threshold(
+    conn: &mut SqliteConnection,
+    wallet_name: &str,
+    threshold: Option<&str>,
+    updated_by: &str,
+    now: i64,
+) -> Result<()> {
+    match threshold {
+        Some(threshold) => {
+            sqlx::query(
+                r#"
+                INSERT INTO review_thresholds (wallet_name, threshold, updated_by, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(wallet_name) DO UPDATE SET
+                    threshold = excluded.threshold,
+                    updated_by = excluded.updated_by,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(wallet_name)
+            .bind(threshold)
+            .bind(updated_by)
+            .bind(now)
+            .execute(conn)
+            .await
+        }
+        None => {
+            sqlx::query("DELETE FROM review_thresholds WHERE wallet_name = ?1").bind(wallet_name).execute(conn).await
+        }
+    }
+    .map_err(|e| anyhow::anyhow!("Failed to store review threshold: {}", e))?;
+    Ok(())
+}
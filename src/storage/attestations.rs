@@ -0,0 +1,170 @@
+//! Signed address-control attestations.
+//!
+//! Every attestation a wallet signed is archived here for auditors, including
+//! expired and revoked ones. Verification looks rows up by signature, so a
+//! revocation holds however the presented document was edited around it.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct AttestationRecord {
+    pub id: String,
+    pub wallet_name: String,
+    pub network: String,
+    /// `eip191` or `bip137`
+    pub scheme: String,
+    pub address: String,
+    pub challenge: String,
+    pub signature: String,
+    pub audience: Option<String>,
+    /// Unix seconds
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub revoked_at: Option<i64>,
+    pub revoked_reason: Option<String>,
+}
+
+impl AttestationRecord {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+/// Fields of a new `attestations` row
+#[derive(Debug, Clone)]
+pub struct NewAttestation<'a> {
+    pub wallet_name: &'a str,
+    pub network: &'a str,
+    pub scheme: &'a str,
+    pub address: &'a str,
+    pub challenge: &'a str,
+    pub signature: &'a str,
+    pub audience: Option<&'a str>,
+    pub expires_at: Option<i64>,
+}
+
+const COLUMNS: &str = "id, wallet_name, network, scheme, address, challenge, signature, audience, created_at, \
+     expires_at, revoked_at, revoked_reason";
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS attestations (
+            id TEXT PRIMARY KEY,
+            wallet_name TEXT NOT NULL,
+            network TEXT NOT NULL,
+            scheme TEXT NOT NULL,
+            address TEXT NOT NULL,
+            challenge TEXT NOT NULL,
+            signature TEXT NOT NULL,
+            audience TEXT,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER,
+            revoked_at INTEGER,
+            revoked_reason TEXT
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_attestations_wallet ON attestations(wallet_name, created_at)")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_attestations_signature ON attestations(signature)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn insert(pool: &SqlitePool, id: &str, attestation: &NewAttestation<'_>, now: i64) -> Result<AttestationRecord> {
+    sqlx::query(
+        r#"
+        INSERT INTO attestations (id, wallet_name, network, scheme, address, challenge, signature, audience,
+                                  created_at, expires_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        "#,
+    )
+    .bind(id)
+    .bind(attestation.wallet_name)
+    .bind(attestation.network)
+    .bind(attestation.scheme)
+    .bind(attestation.address)
+    .bind(attestation.challenge)
+    .bind(attestation.signature)
+    .bind(attestation.audience)
+    .bind(now)
+    .bind(attestation.expires_at)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store attestation: {}", e))?;
+    Ok(AttestationRecord {
+        id: id.to_string(),
+        wallet_name: attestation.wallet_name.to_string(),
+        network: attestation.network.to_string(),
+        scheme: attestation.scheme.to_string(),
+        address: attestation.address.to_string(),
+        challenge: attestation.challenge.to_string(),
+        signature: attestation.signature.to_string(),
+        audience: attestation.audience.map(str::to_string),
+        created_at: now,
+        expires_at: attestation.expires_at,
+        revoked_at: None,
+        revoked_reason: None,
+    })
+}
+
+pub async fn find_by_signature(pool: &SqlitePool, signature: &str) -> Result<Option<AttestationRecord>> {
+    sqlx::query_as::<_, AttestationRecord>(&format!(
+        "SELECT {} FROM attestations WHERE signature = ?1 ORDER BY created_at LIMIT 1",
+        COLUMNS
+    ))
+    .bind(signature)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to look up attestation: {}", e))
+}
+
+/// Newest first
+pub async fn list_for_wallet(pool: &SqlitePool, wallet_name: &str) -> Result<Vec<AttestationRecord>> {
+    sqlx::query_as::<_, AttestationRecord>(&format!(
+        "SELECT {} FROM attestations WHERE wallet_name = ?1 ORDER BY created_at DESC, id DESC",
+        COLUMNS
+    ))
+    .bind(wallet_name)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to list attestations: {}", e))
+}
+
+/// `false` if the wallet has no such unrevoked attestation
+pub async fn revoke(
+    pool: &SqlitePool,
+    wallet_name: &str,
+    id: &str,
+    reason: Option<&str>,
+    now: i64,
+) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE attestations SET revoked_at = ?1, revoked_reason = ?2 \
+         WHERE wallet_name = ?3 AND id = ?4 AND revoked_at IS NULL",
+    )
+    .bind(now)
+    .bind(reason)
+    .bind(wallet_name)
+    .bind(id)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to revoke attestation: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn rename(conn: &mut sqlx::SqliteConnection, wallet_name: &str, new_name: &str) -> Result<()> {
+    sqlx::query("UPDATE attestations SET wallet_name = ?1 WHERE wallet_name = ?2")
+        .bind(new_name)
+        .bind(wallet_name)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
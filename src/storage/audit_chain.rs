@@ -0,0 +1,210 @@
+//! Previous-MAC chain over the audit log.
+//!
+//! Each audit row's MAC (in `audit_logs_hmac`) covers its own content, which
+//! catches edits but not a row deleted together with its MAC. So every row
+//! also stores `prev_mac`, the MAC of the row before it, and its own MAC
+//! covers that link: removing a row leaves the next one pointing at a MAC
+//! that no longer exists. The first row is the genesis and links to nothing.
+//! Rows written before the chain existed are linked once, when the column is
+//! added, with the oldest of them as the genesis.
+//!
+//! The chain cannot tell a truncated tail from a log that simply ends there.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+use super::WalletStorage;
+
+/// Outcome of walking the chain from the genesis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditChainReport {
+    pub total_entries: u64,
+    /// Entries that verified before the first broken link
+    pub verified: u64,
+    /// Audit id of the first entry whose MAC or link does not verify
+    pub broken_at: Option<i64>,
+    pub reason: Option<String>,
+}
+
+impl AuditChainReport {
+    pub fn is_intact(&self) -> bool {
+        self.broken_at.is_none()
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub(super) struct ChainRow {
+    pub id: i64,
+    pub wallet_id: Option<String>,
+    pub action: String,
+    pub details: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    mac: Option<String>,
+    prev_mac: Option<String>,
+}
+
+impl ChainRow {
+    fn compute_mac(&self) -> Result<String> {
+        WalletStorage::compute_audit_mac(
+            self.id,
+            self.wallet_id.as_deref().unwrap_or(""),
+            &self.action,
+            self.details.as_deref().unwrap_or(""),
+            self.ip_address.as_deref(),
+            self.user_agent.as_deref(),
+            self.prev_mac.as_deref(),
+        )
+    }
+}
+
+/// Row, MAC and link of every entry; plain SQL, so the PostgreSQL store reads the same shape
+pub(super) const CHAIN_SELECT: &str = "SELECT a.id, a.wallet_id, a.action, a.details, a.ip_address, a.user_agent, \
+     h.mac, h.prev_mac FROM audit_logs a LEFT JOIN audit_logs_hmac h ON h.audit_id = a.id";
+
+/// Adds `audit_logs_hmac.prev_mac` and links the rows already there. Runs in
+/// one transaction, so a failed backfill is retried on the next start.
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    let has_prev_mac: bool =
+        sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_table_info('audit_logs_hmac') WHERE name = 'prev_mac'")
+            .fetch_one(pool)
+            .await?;
+    if has_prev_mac {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("ALTER TABLE audit_logs_hmac ADD COLUMN prev_mac TEXT")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to add audit_logs_hmac.prev_mac: {}", e))?;
+    let rows: Vec<ChainRow> = sqlx::query_as(&format!("{} ORDER BY a.id", CHAIN_SELECT)).fetch_all(&mut *tx).await?;
+    let mut prev: Option<String> = None;
+    for mut row in rows {
+        // a row without a MAC stays broken; the next one links past it
+        if row.mac.is_none() {
+            continue;
+        }
+        if prev.is_some() {
+            row.prev_mac = prev;
+            row.mac = Some(row.compute_mac()?);
+            store_mac(&mut tx, &row).await?;
+        }
+        prev = row.mac;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// MAC of the newest row below `audit_id`, which a row inserted as `audit_id` links to
+pub(super) async fn prev_mac(conn: &mut SqliteConnection, audit_id: i64) -> Result<Option<String>> {
+    let mac = sqlx::query_scalar("SELECT mac FROM audit_logs_hmac WHERE audit_id < ?1 ORDER BY audit_id DESC LIMIT 1")
+        .bind(audit_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load previous audit mac: {}", e))?;
+    Ok(mac)
+}
+
+/// Applies `edit` to the rows in `ids` and re-MACs them, relinking every later
+/// row whose predecessor's MAC changed. A link that was already broken is left
+/// broken. Returns the ids `edit` changed.
+pub(super) async fn rewrite<F>(conn: &mut SqliteConnection, ids: &HashSet<i64>, mut edit: F) -> Result<Vec<i64>>
+where
+    F: FnMut(&mut ChainRow) -> bool,
+{
+    let Some(first) = ids.iter().min() else {
+        return Ok(Vec::new());
+    };
+    let rows: Vec<ChainRow> = sqlx::query_as(&format!("{} WHERE a.id >= ?1 ORDER BY a.id", CHAIN_SELECT))
+        .bind(first)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load audit rows: {}", e))?;
+
+    let mut remaced: HashMap<String, String> = HashMap::new();
+    let mut edited = Vec::new();
+    for mut row in rows {
+        let changed = ids.contains(&row.id) && edit(&mut row);
+        let relinked = row.prev_mac.as_ref().and_then(|prev| remaced.get(prev)).cloned();
+        if !changed && relinked.is_none() {
+            continue;
+        }
+        if relinked.is_some() {
+            row.prev_mac = relinked;
+        }
+        let old_mac = row.mac.take();
+        let mac = row.compute_mac()?;
+        if let Some(old_mac) = old_mac {
+            remaced.insert(old_mac, mac.clone());
+        }
+        row.mac = Some(mac);
+        if changed {
+            sqlx::query(
+                "UPDATE audit_logs SET wallet_id = ?1, action = ?2, details = ?3, ip_address = ?4, user_agent = ?5 \
+                 WHERE id = ?6",
+            )
+            .bind(&row.wallet_id)
+            .bind(&row.action)
+            .bind(&row.details)
+            .bind(&row.ip_address)
+            .bind(&row.user_agent)
+            .bind(row.id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to rewrite audit row {}: {}", row.id, e))?;
+            edited.push(row.id);
+        }
+        store_mac(conn, &row).await?;
+    }
+    Ok(edited)
+}
+
+async fn store_mac(conn: &mut SqliteConnection, row: &ChainRow) -> Result<()> {
+    sqlx::query("INSERT OR REPLACE INTO audit_logs_hmac (audit_id, mac, prev_mac) VALUES (?1, ?2, ?3)")
+        .bind(row.id)
+        .bind(&row.mac)
+        .bind(&row.prev_mac)
+        .execute(conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to re-MAC audit row {}: {}", row.id, e))?;
+    Ok(())
+}
+
+/// Walks the whole log in id order and stops at the first entry whose MAC is
+/// missing or wrong, or whose link is not the MAC of the entry before it.
+pub async fn verify(conn: &mut SqliteConnection) -> Result<AuditChainReport> {
+    let rows: Vec<ChainRow> = sqlx::query_as(&format!("{} ORDER BY a.id", CHAIN_SELECT))
+        .fetch_all(conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load audit chain: {}", e))?;
+    walk(&rows)
+}
+
+/// The check behind [`verify`] over rows already loaded in id order
+pub(super) fn walk(rows: &[ChainRow]) -> Result<AuditChainReport> {
+    let mut report = AuditChainReport { total_entries: rows.len() as u64, verified: 0, broken_at: None, reason: None };
+    let mut prev: Option<&str> = None;
+    for row in rows {
+        let reason = match row.mac.as_deref() {
+            None => Some("missing MAC"),
+            Some(_) if row.prev_mac.as_deref() != prev => Some(match prev {
+                None => "links to an entry before the genesis",
+                Some(_) => "does not link to the previous entry",
+            }),
+            Some(mac) if row.compute_mac()? != mac => Some("MAC mismatch"),
+            Some(_) => None,
+        };
+        if let Some(reason) = reason {
+            report.broken_at = Some(row.id);
+            report.reason = Some(reason.to_string());
+            return Ok(report);
+        }
+        report.verified += 1;
+        prev = row.mac.as_deref();
+    }
+    Ok(report)
+}
@@ -0,0 +1,133 @@
+//! Outcome of every database backup run.
+//!
+//! A row is written per attempt, including failed ones, so operators can see
+//! when the last good backup was taken. Pruned backups keep their row with
+//! `pruned_at` set; only the stored object is deleted.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow};
+
+/// Backup written and passed verification.
+pub const BACKUP_VERIFIED: &str = "verified";
+/// Backup could not be written or failed verification.
+pub const BACKUP_FAILED: &str = "failed";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct BackupRecord {
+    pub id: i64,
+    /// Object name inside the sink
+    pub object_key: String,
+    /// `filesystem` or `s3`
+    pub sink: String,
+    /// `verified` or `failed`
+    pub status: String,
+    /// Encrypted size in bytes
+    pub size_bytes: i64,
+    /// SHA-256 of the encrypted object (hex)
+    pub sha256: Option<String>,
+    pub error: Option<String>,
+    /// Unix seconds
+    pub created_at: i64,
+    pub pruned_at: Option<i64>,
+}
+
+/// Fields of a new `backup_history` row.
+#[derive(Debug, Clone)]
+pub struct NewBackupRecord<'a> {
+    pub object_key: &'a str,
+    pub sink: &'a str,
+    pub status: &'a str,
+    pub size_bytes: i64,
+    pub sha256: Option<&'a str>,
+    pub error: Option<&'a str>,
+    pub created_at: i64,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS backup_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            object_key TEXT NOT NULL,
+            sink TEXT NOT NULL,
+            status TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL DEFAULT 0,
+            sha256 TEXT,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            pruned_at INTEGER
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_backup_history_created ON backup_history (created_at)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn insert(pool: &SqlitePool, record: &NewBackupRecord<'_>) -> Result<i64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO backup_history (object_key, sink, status, size_bytes, sha256, error, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "#,
+    )
+    .bind(record.object_key)
+    .bind(record.sink)
+    .bind(record.status)
+    .bind(record.size_bytes)
+    .bind(record.sha256)
+    .bind(record.error)
+    .bind(record.created_at)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record backup: {}", e))?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Most recent rows first.
+pub async fn list(pool: &SqlitePool, limit: i64) -> Result<Vec<BackupRecord>> {
+    sqlx::query_as::<_, BackupRecord>(
+        r#"
+        SELECT id, object_key, sink, status, size_bytes, sha256, error, created_at, pruned_at
+        FROM backup_history
+        ORDER BY created_at DESC, id DESC
+        LIMIT ?1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load backup history: {}", e))
+}
+
+/// Rows whose object has not been pruned yet, most recent first.
+pub async fn unpruned(pool: &SqlitePool) -> Result<Vec<BackupRecord>> {
+    sqlx::query_as::<_, BackupRecord>(
+        r#"
+        SELECT id, object_key, sink, status, size_bytes, sha256, error, created_at, pruned_at
+        FROM backup_history
+        WHERE pruned_at IS NULL
+        ORDER BY created_at DESC, id DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load backup history: {}", e))
+}
+
+pub async fn mark_pruned(pool: &SqlitePool, id: i64, pruned_at: i64) -> Result<()> {
+    sqlx::query("UPDATE backup_history SET pruned_at = ?1 WHERE id = ?2")
+        .bind(pruned_at)
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to mark backup pruned: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,313 @@
+//! Balance snapshots backing the balance-over-time charts.
+//!
+//! A row is only written when a balance differs from the previous snapshot of
+//! the same series, so reading a series means carrying the last known value
+//! forward. Rows past the hourly retention window are thinned to the last
+//! snapshot of each UTC day.
+
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow};
+
+/// `token` value of native-coin series.
+pub const NATIVE_TOKEN: &str = "native";
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct BalanceSnapshot {
+    pub wallet_id: String,
+    pub network: String,
+    pub token: String,
+    pub balance: String,
+    pub block_number: Option<i64>,
+    /// Unix seconds
+    pub captured_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotResolution {
+    #[default]
+    Hour,
+    Day,
+}
+
+impl SnapshotResolution {
+    pub fn step_secs(self) -> i64 {
+        match self {
+            SnapshotResolution::Hour => 3_600,
+            SnapshotResolution::Day => 86_400,
+        }
+    }
+}
+
+/// One bucket of a balance series.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalancePoint {
+    /// Bucket start
+    pub timestamp: DateTime<Utc>,
+    /// Balance at the end of the bucket
+    pub balance: String,
+    pub block_number: Option<i64>,
+    /// No snapshot fell inside the bucket; the previous value was carried forward.
+    pub interpolated: bool,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS balance_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            wallet_id TEXT NOT NULL,
+            network TEXT NOT NULL,
+            token TEXT NOT NULL DEFAULT 'native',
+            balance TEXT NOT NULL,
+            block_number INTEGER,
+            captured_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_balance_snapshots_series \
+         ON balance_snapshots (wallet_id, network, token, captured_at)",
+    )
+    .execute(pool)
+    .await?;
+
+    // fiat price of each priced series' coin, captured alongside the balances
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS price_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            base TEXT NOT NULL,
+            quote TEXT NOT NULL,
+            price TEXT NOT NULL,
+            captured_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_price_snapshots_pair ON price_snapshots (base, quote, captured_at)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// `price` is a decimal string.
+pub async fn insert_price(pool: &SqlitePool, base: &str, quote: &str, price: &str, captured_at: i64) -> Result<()> {
+    sqlx::query("INSERT INTO price_snapshots (base, quote, price, captured_at) VALUES (?1, ?2, ?3, ?4)")
+        .bind(base)
+        .bind(quote)
+        .bind(price)
+        .bind(captured_at)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to record price snapshot: {}", e))?;
+    Ok(())
+}
+
+/// Latest price with `not_before <= captured_at <= at`.
+pub async fn price_at(pool: &SqlitePool, base: &str, quote: &str, at: i64, not_before: i64) -> Result<Option<String>> {
+    sqlx::query_scalar(
+        r#"
+        SELECT price FROM price_snapshots
+        WHERE base = ?1 AND quote = ?2 AND captured_at <= ?3 AND captured_at >= ?4
+        ORDER BY captured_at DESC, id DESC LIMIT 1
+        "#,
+    )
+    .bind(base)
+    .bind(quote)
+    .bind(at)
+    .bind(not_before)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load price snapshot: {}", e))
+}
+
+/// Inserts `snapshot` unless the series' latest balance is identical.
+/// Returns whether a row was written.
+pub async fn record_if_changed(pool: &SqlitePool, snapshot: &BalanceSnapshot) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO balance_snapshots (wallet_id, network, token, balance, block_number, captured_at)
+        SELECT ?1, ?2, ?3, ?4, ?5, ?6
+        WHERE COALESCE((
+            SELECT balance FROM balance_snapshots
+            WHERE wallet_id = ?1 AND network = ?2 AND token = ?3
+            ORDER BY captured_at DESC, id DESC LIMIT 1
+        ), '') <> ?4
+        "#,
+    )
+    .bind(&snapshot.wallet_id)
+    .bind(&snapshot.network)
+    .bind(&snapshot.token)
+    .bind(&snapshot.balance)
+    .bind(snapshot.block_number)
+    .bind(snapshot.captured_at)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record balance snapshot: {}", e))?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Latest snapshot strictly before `before`.
+pub async fn latest_before(
+    pool: &SqlitePool,
+    wallet_id: &str,
+    network: &str,
+    token: &str,
+    before: i64,
+) -> Result<Option<BalanceSnapshot>> {
+    sqlx::query_as::<_, BalanceSnapshot>(
+        r#"
+        SELECT wallet_id, network, token, balance, block_number, captured_at
+        FROM balance_snapshots
+        WHERE wallet_id = ?1 AND network = ?2 AND token = ?3 AND captured_at < ?4
+        ORDER BY captured_at DESC, id DESC LIMIT 1
+        "#,
+    )
+    .bind(wallet_id)
+    .bind(network)
+    .bind(token)
+    .bind(before)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load balance snapshot: {}", e))
+}
+
+/// Snapshots with `from <= captured_at < to`, oldest first.
+pub async fn range(
+    pool: &SqlitePool,
+    wallet_id: &str,
+    network: &str,
+    token: &str,
+    from: i64,
+    to: i64,
+) -> Result<Vec<BalanceSnapshot>> {
+    sqlx::query_as::<_, BalanceSnapshot>(
+        r#"
+        SELECT wallet_id, network, token, balance, block_number, captured_at
+        FROM balance_snapshots
+        WHERE wallet_id = ?1 AND network = ?2 AND token = ?3
+          AND captured_at >= ?4 AND captured_at < ?5
+        ORDER BY captured_at ASC, id ASC
+        "#,
+    )
+    .bind(wallet_id)
+    .bind(network)
+    .bind(token)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load balance snapshots: {}", e))
+}
+
+/// Keeps only the last snapshot per series and UTC day for rows older than
+/// `cutoff`. Returns the number of rows deleted.
+pub async fn downsample(pool: &SqlitePool, cutoff: i64) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM balance_snapshots
+        WHERE captured_at < ?1 AND id NOT IN (
+            SELECT id FROM (
+                SELECT id, ROW_NUMBER() OVER (
+                    PARTITION BY wallet_id, network, token, captured_at / 86400
+                    ORDER BY captured_at DESC, id DESC
+                ) AS rn
+                FROM balance_snapshots
+                WHERE captured_at < ?1
+            ) WHERE rn = 1
+        )
+        "#,
+    )
+    .bind(cutoff)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to downsample balance snapshots: {}", e))?;
+
+    Ok(result.rows_affected())
+}
+
+/// Buckets `rows` (oldest first, all `>= from`) into a `resolution` series
+/// over `[from, to)`. `seed` is the last snapshot before `from`; buckets before
+/// the first known balance are omitted.
+pub fn build_series(
+    seed: Option<&BalanceSnapshot>,
+    rows: &[BalanceSnapshot],
+    from: i64,
+    to: i64,
+    resolution: SnapshotResolution,
+) -> Vec<BalancePoint> {
+    let step = resolution.step_secs();
+    let mut points = Vec::new();
+    let mut current = seed;
+    let mut idx = 0;
+    let mut bucket = from.div_euclid(step) * step;
+
+    while bucket < to {
+        let bucket_end = bucket + step;
+        let mut observed = false;
+        while idx < rows.len() && rows[idx].captured_at < bucket_end {
+            current = Some(&rows[idx]);
+            observed = true;
+            idx += 1;
+        }
+        if let (Some(snap), Some(timestamp)) = (current, Utc.timestamp_opt(bucket, 0).single()) {
+            points.push(BalancePoint {
+                timestamp,
+                balance: snap.balance.clone(),
+                block_number: snap.block_number,
+                interpolated: !observed,
+            });
+        }
+        bucket = bucket_end;
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(balance: &str, captured_at: i64) -> BalanceSnapshot {
+        BalanceSnapshot {
+            wallet_id: "w".into(),
+            network: "eth".into(),
+            token: NATIVE_TOKEN.into(),
+            balance: balance.into(),
+            block_number: Some(captured_at / 12),
+            captured_at,
+        }
+    }
+
+    #[test]
+    fn test_series_carries_values_forward() {
+        let seed = snap("1.0", 100);
+        let rows = vec![snap("2.0", 7_300), snap("3.0", 7_400)];
+        let points = build_series(Some(&seed), &rows, 3_600, 4 * 3_600, SnapshotResolution::Hour);
+
+        let got: Vec<_> = points.iter().map(|p| (p.balance.as_str(), p.interpolated)).collect();
+        assert_eq!(got, vec![("1.0", true), ("3.0", false), ("3.0", true)]);
+        assert_eq!(points[1].timestamp.timestamp(), 7_200);
+    }
+
+    #[test]
+    fn test_series_skips_buckets_before_first_snapshot() {
+        let rows = vec![snap("5", 86_400 * 2 + 10)];
+        let points = build_series(None, &rows, 0, 86_400 * 4, SnapshotResolution::Day);
+        assert_eq!(points.len(), 2);
+        assert!(!points[0].interpolated);
+        assert!(points[1].interpolated);
+    }
+}
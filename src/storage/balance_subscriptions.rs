@@ -0,0 +1,367 @@
+//! Balance change subscriptions: "notify webhook target T whenever wallet X's
+//! balance of token K on network N moves by at least `min_delta`".
+//!
+//! `wallet_id` is the `user_wallets` row id in users.db, as for wallet
+//! tokens. Balances are compared in minimal units (wei, satoshi, token base
+//! units) against the balance last reported to the subscriber, so a run of
+//! small deposits adds up until it crosses the threshold.
+//!
+//! Evaluation state lives in the row (`last_notified_balance` plus the open
+//! debounce window), which makes re-observing the same balance after a
+//! restart a no-op instead of a duplicate notification.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct BalanceSubscriptionRecord {
+    pub id: String,
+    pub wallet_id: i64,
+    pub wallet_name: String,
+    pub owner_user_id: String,
+    pub network: String,
+    /// [`super::NATIVE_TOKEN`] or a lowercase token contract address
+    pub token: String,
+    /// Minimal units, decimal
+    pub min_delta: String,
+    /// Opaque routing key copied into every `balance.changed` event
+    pub webhook_target: String,
+    pub active: bool,
+    /// Balance carried by the last event (or the baseline); minimal units
+    pub last_notified_balance: Option<String>,
+    /// Unix seconds of the first unreported change in the open debounce window
+    pub pending_since: Option<i64>,
+    /// Latest transaction known to have touched the balance in that window
+    pub pending_tx_hash: Option<String>,
+    /// Owner user id, or `admin`
+    pub created_by: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Fields of a new `balance_subscriptions` row.
+#[derive(Debug, Clone)]
+pub struct NewBalanceSubscription<'a> {
+    pub id: &'a str,
+    pub wallet_id: i64,
+    pub wallet_name: &'a str,
+    pub owner_user_id: &'a str,
+    pub network: &'a str,
+    pub token: &'a str,
+    pub min_delta: u128,
+    pub webhook_target: &'a str,
+    pub created_by: &'a str,
+}
+
+/// A balance read by the deposit scanner or the snapshot task.
+#[derive(Debug, Clone)]
+pub struct BalanceObservation<'a> {
+    pub wallet_id: i64,
+    pub network: &'a str,
+    pub token: &'a str,
+    /// Minimal units
+    pub balance: u128,
+    /// Transaction that produced this balance, when the source knows it
+    pub tx_hash: Option<&'a str>,
+}
+
+/// What one observation does to a subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionStep {
+    /// No balance recorded yet: the observation becomes the baseline
+    Baseline,
+    /// Within `min_delta` of the last reported balance; an open window is dropped
+    Quiet,
+    /// Changed enough, but the debounce window that opened at `since` is still open
+    Pending { since: i64 },
+    /// Report `old -> observed`; the window opened at `first_change_at`
+    Notify { old: u128, first_change_at: i64 },
+}
+
+impl BalanceSubscriptionRecord {
+    pub fn min_delta_units(&self) -> Result<u128> {
+        self.min_delta
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Corrupt min_delta on balance subscription {}", self.id))
+    }
+
+    /// Pure transition for `balance` observed at `now`; see [`SubscriptionStep`].
+    pub fn evaluate(&self, balance: u128, debounce_secs: u64, now: i64) -> Result<SubscriptionStep> {
+        let Some(last) = self.last_notified_balance.as_deref() else {
+            return Ok(SubscriptionStep::Baseline);
+        };
+        let old: u128 = last
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Corrupt last_notified_balance on balance subscription {}", self.id))?;
+        if old.abs_diff(balance) < self.min_delta_units()?.max(1) {
+            return Ok(SubscriptionStep::Quiet);
+        }
+        let since = self.pending_since.unwrap_or(now);
+        if now.saturating_sub(since) < debounce_secs as i64 {
+            return Ok(SubscriptionStep::Pending { since });
+        }
+        Ok(SubscriptionStep::Notify { old, first_change_at: since })
+    }
+}
+
+/// Signed decimal `new - old`
+pub fn signed_delta(old: u128, new: u128) -> String {
+    if new >= old {
+        (new - old).to_string()
+    } else {
+        format!("-{}", old - new)
+    }
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS balance_subscriptions (
+            id TEXT PRIMARY KEY,
+            wallet_id INTEGER NOT NULL,
+            wallet_name TEXT NOT NULL,
+            owner_user_id TEXT NOT NULL,
+            network TEXT NOT NULL,
+            token TEXT NOT NULL,
+            min_delta TEXT NOT NULL,
+            webhook_target TEXT NOT NULL,
+            active INTEGER NOT NULL DEFAULT 1,
+            last_notified_balance TEXT,
+            pending_since INTEGER,
+            pending_tx_hash TEXT,
+            created_by TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // 每次余额观测都按 (wallet, network, token) 查找活跃订阅
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_balance_subscriptions_target \
+         ON balance_subscriptions (wallet_id, network, token)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert(pool: &SqlitePool, sub: &NewBalanceSubscription<'_>, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO balance_subscriptions (
+            id, wallet_id, wallet_name, owner_user_id, network, token, min_delta,
+            webhook_target, active, created_by, created_at, updated_at
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 1, ?9, ?10, ?10)
+        "#,
+    )
+    .bind(sub.id)
+    .bind(sub.wallet_id)
+    .bind(sub.wallet_name)
+    .bind(sub.owner_user_id)
+    .bind(sub.network)
+    .bind(sub.token)
+    .bind(sub.min_delta.to_string())
+    .bind(sub.webhook_target)
+    .bind(sub.created_by)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store balance subscription: {}", e))?;
+    Ok(())
+}
+
+const RECORD_COLUMNS: &str = "id, wallet_id, wallet_name, owner_user_id, network, token, min_delta, \
+                              webhook_target, active, last_notified_balance, pending_since, \
+                              pending_tx_hash, created_by, created_at, updated_at";
+
+pub async fn get(pool: &SqlitePool, wallet_id: i64, id: &str) -> Result<Option<BalanceSubscriptionRecord>> {
+    sqlx::query_as::<_, BalanceSubscriptionRecord>(&format!(
+        "SELECT {} FROM balance_subscriptions WHERE wallet_id = ?1 AND id = ?2",
+        RECORD_COLUMNS
+    ))
+    .bind(wallet_id)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load balance subscription: {}", e))
+}
+
+/// Subscriptions of one wallet, oldest first, inactive ones included.
+pub async fn list_for_wallet(pool: &SqlitePool, wallet_id: i64) -> Result<Vec<BalanceSubscriptionRecord>> {
+    sqlx::query_as::<_, BalanceSubscriptionRecord>(&format!(
+        "SELECT {} FROM balance_subscriptions WHERE wallet_id = ?1 ORDER BY created_at, rowid",
+        RECORD_COLUMNS
+    ))
+    .bind(wallet_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load balance subscriptions: {}", e))
+}
+
+/// Changes `active` and/or `min_delta`; `false` if the subscription does not
+/// belong to the wallet. Reactivating keeps the last reported balance, so
+/// changes made while inactive are reported on the next observation.
+pub async fn update(
+    pool: &SqlitePool,
+    wallet_id: i64,
+    id: &str,
+    active: Option<bool>,
+    min_delta: Option<u128>,
+    now: i64,
+) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE balance_subscriptions SET active = COALESCE(?3, active), \
+         min_delta = COALESCE(?4, min_delta), updated_at = ?5 WHERE wallet_id = ?1 AND id = ?2",
+    )
+    .bind(wallet_id)
+    .bind(id)
+    .bind(active)
+    .bind(min_delta.map(|d| d.to_string()))
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to update balance subscription: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn delete(pool: &SqlitePool, wallet_id: i64, id: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM balance_subscriptions WHERE wallet_id = ?1 AND id = ?2")
+        .bind(wallet_id)
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to delete balance subscription: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Deactivates every subscription of a deleted wallet; returns how many were active.
+pub async fn deactivate_for_wallet(pool: &SqlitePool, wallet_id: i64, now: i64) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE balance_subscriptions SET active = 0, pending_since = NULL, pending_tx_hash = NULL, \
+         updated_at = ?2 WHERE wallet_id = ?1 AND active = 1",
+    )
+    .bind(wallet_id)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to deactivate balance subscriptions: {}", e))?;
+    Ok(result.rows_affected())
+}
+
+/// Active subscriptions matching an observation.
+pub async fn active_for(
+    conn: &mut SqliteConnection,
+    wallet_id: i64,
+    network: &str,
+    token: &str,
+) -> Result<Vec<BalanceSubscriptionRecord>> {
+    sqlx::query_as::<_, BalanceSubscriptionRecord>(&format!(
+        "SELECT {} FROM balance_subscriptions \
+         WHERE wallet_id = ?1 AND network = ?2 AND token = ?3 AND active = 1 ORDER BY rowid",
+        RECORD_COLUMNS
+    ))
+    .bind(wallet_id)
+    .bind(network)
+    .bind(token)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load balance subscriptions: {}", e))
+}
+
+/// Persists `step` for subscription `id` after observing `balance`.
+pub async fn apply(
+    conn: &mut SqliteConnection,
+    id: &str,
+    step: SubscriptionStep,
+    balance: u128,
+    tx_hash: Option<&str>,
+    now: i64,
+) -> Result<()> {
+    let query = match step {
+        SubscriptionStep::Baseline | SubscriptionStep::Notify { .. } => sqlx::query(
+            "UPDATE balance_subscriptions SET last_notified_balance = ?2, pending_since = NULL, \
+             pending_tx_hash = NULL, updated_at = ?3 WHERE id = ?1",
+        )
+        .bind(id)
+        .bind(balance.to_string())
+        .bind(now),
+        SubscriptionStep::Quiet => sqlx::query(
+            "UPDATE balance_subscriptions SET pending_since = NULL, pending_tx_hash = NULL, updated_at = ?2 \
+             WHERE id = ?1 AND pending_since IS NOT NULL",
+        )
+        .bind(id)
+        .bind(now),
+        SubscriptionStep::Pending { since } => sqlx::query(
+            "UPDATE balance_subscriptions SET pending_since = ?2, \
+             pending_tx_hash = COALESCE(?3, pending_tx_hash), updated_at = ?4 WHERE id = ?1",
+        )
+        .bind(id)
+        .bind(since)
+        .bind(tx_hash)
+        .bind(now),
+    };
+    query
+        .execute(conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update balance subscription state: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::NATIVE_TOKEN;
+
+    fn record(last: Option<&str>, pending_since: Option<i64>) -> BalanceSubscriptionRecord {
+        BalanceSubscriptionRecord {
+            id: "sub".to_string(),
+            wallet_id: 1,
+            wallet_name: "main".to_string(),
+            owner_user_id: "u".to_string(),
+            network: "eth".to_string(),
+            token: NATIVE_TOKEN.to_string(),
+            min_delta: "100".to_string(),
+            webhook_target: "ops".to_string(),
+            active: true,
+            last_notified_balance: last.map(str::to_string),
+            pending_since,
+            pending_tx_hash: None,
+            created_by: "u".to_string(),
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_transitions() {
+        assert_eq!(record(None, None).evaluate(5, 60, 1_000).unwrap(), SubscriptionStep::Baseline);
+        assert_eq!(record(Some("1000"), None).evaluate(1_099, 60, 1_000).unwrap(), SubscriptionStep::Quiet);
+        // 差值恰好等于 min_delta 也算变化
+        assert_eq!(
+            record(Some("1000"), None).evaluate(900, 0, 1_000).unwrap(),
+            SubscriptionStep::Notify { old: 1_000, first_change_at: 1_000 }
+        );
+        assert_eq!(
+            record(Some("1000"), Some(990)).evaluate(1_200, 60, 1_000).unwrap(),
+            SubscriptionStep::Pending { since: 990 }
+        );
+        assert_eq!(
+            record(Some("1000"), Some(940)).evaluate(1_200, 60, 1_000).unwrap(),
+            SubscriptionStep::Notify { old: 1_000, first_change_at: 940 }
+        );
+        assert!(record(Some("oops"), None).evaluate(1, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_signed_delta() {
+        assert_eq!(signed_delta(10, 25), "15");
+        assert_eq!(signed_delta(25, 10), "-15");
+        assert_eq!(signed_delta(0, u128::MAX), u128::MAX.to_string());
+    }
+}
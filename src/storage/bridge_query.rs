@@ -0,0 +1,152 @@
+//! Listing queries over the `bridge_transactions` table.
+//!
+//! Rows are ordered newest first by `(created_at, id)`. The offset form backs
+//! the deprecated `page`/`page_size` parameters; the keyset form seeks past
+//! the last row a client saw, so rows inserted while a client is paging do
+//! not shift the later pages.
+
+use anyhow::Result;
+use sqlx::{sqlite::SqliteRow, QueryBuilder, Row, Sqlite, SqlitePool};
+
+use super::TransactionCursor;
+use crate::blockchain::bridge::{BridgeTransaction, BridgeTransactionStatus};
+
+/// Filter for bridge listings; `None` fields do not constrain the query.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BridgeFilter<'a> {
+    pub wallet_name: Option<&'a str>,
+    pub from_chain: Option<&'a str>,
+    pub to_chain: Option<&'a str>,
+}
+
+/// Adds the normalized amount and token columns to tables created before
+/// them; older rows keep `NULL` there.
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    for column in ["amount_minimal_source", "amount_minimal_dest", "token_address_source", "token_address_dest"] {
+        let has_column: bool =
+            sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_table_info('bridge_transactions') WHERE name = ?")
+                .bind(column)
+                .fetch_one(pool)
+                .await?;
+        if !has_column {
+            sqlx::query(&format!("ALTER TABLE bridge_transactions ADD COLUMN {} TEXT", column))
+                .execute(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to add bridge_transactions.{}: {}", column, e))?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn init_indexes(pool: &SqlitePool) -> Result<()> {
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_bridge_created_id ON bridge_transactions (created_at, id)")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_bridge_status_updated ON bridge_transactions (status, updated_at)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+fn push_where<'a>(qb: &mut QueryBuilder<'a, Sqlite>, filter: &BridgeFilter<'a>) {
+    qb.push(" WHERE 1 = 1");
+    if let Some(wallet) = filter.wallet_name {
+        qb.push(" AND from_wallet = ").push_bind(wallet);
+    }
+    if let Some(from) = filter.from_chain {
+        qb.push(" AND from_chain = ").push_bind(from);
+    }
+    if let Some(to) = filter.to_chain {
+        qb.push(" AND to_chain = ").push_bind(to);
+    }
+}
+
+pub(crate) fn from_row(row: &SqliteRow) -> Result<BridgeTransaction> {
+    let status_str: String = row.get("status");
+    let status: BridgeTransactionStatus = serde_json::from_str(&status_str)
+        .map_err(|e| anyhow::anyhow!("Failed to parse bridge status: {}", e))?;
+    Ok(BridgeTransaction {
+        id: row.get("id"),
+        from_wallet: row.get("from_wallet"),
+        from_chain: row.get("from_chain"),
+        to_chain: row.get("to_chain"),
+        token: row.get("token"),
+        amount: row.get("amount"),
+        status,
+        source_tx_hash: row.get("source_tx_hash"),
+        destination_tx_hash: row.get("destination_tx_hash"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        fee_amount: row.get("fee_amount"),
+        estimated_completion_time: row.get("estimated_completion_time"),
+        amount_minimal_source: row.get("amount_minimal_source"),
+        amount_minimal_dest: row.get("amount_minimal_dest"),
+        token_address_source: row.get("token_address_source"),
+        token_address_dest: row.get("token_address_dest"),
+    })
+}
+
+pub async fn count(pool: &SqlitePool, filter: &BridgeFilter<'_>) -> Result<usize> {
+    let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT COUNT(*) FROM bridge_transactions");
+    push_where(&mut qb, filter);
+    let total: i64 = qb.build_query_scalar().fetch_one(pool).await?;
+    Ok(total.max(0) as usize)
+}
+
+/// `limit` rows starting `offset` rows into the listing.
+pub async fn offset_page(
+    pool: &SqlitePool,
+    filter: &BridgeFilter<'_>,
+    offset: usize,
+    limit: usize,
+) -> Result<Vec<BridgeTransaction>> {
+    let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT * FROM bridge_transactions");
+    push_where(&mut qb, filter);
+    qb.push(" ORDER BY created_at DESC, id DESC LIMIT ")
+        .push_bind(limit as i64)
+        .push(" OFFSET ")
+        .push_bind(offset as i64);
+    qb.build().fetch_all(pool).await?.iter().map(from_row).collect()
+}
+
+/// Up to `limit` rows after `cursor`, plus the cursor of the next page.
+pub async fn keyset_page(
+    pool: &SqlitePool,
+    filter: &BridgeFilter<'_>,
+    cursor: Option<&TransactionCursor>,
+    limit: usize,
+) -> Result<(Vec<BridgeTransaction>, Option<TransactionCursor>)> {
+    let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT * FROM bridge_transactions");
+    push_where(&mut qb, filter);
+    if let Some(c) = cursor {
+        qb.push(" AND (created_at, id) < (").push_bind(c.created_at).push(", ").push_bind(c.id.clone()).push(")");
+    }
+    // One extra row tells whether another page exists.
+    qb.push(" ORDER BY created_at DESC, id DESC LIMIT ").push_bind((limit + 1) as i64);
+
+    let mut rows = qb.build().fetch_all(pool).await?.iter().map(from_row).collect::<Result<Vec<_>>>()?;
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+    let next = if has_more {
+        rows.last().map(|t| TransactionCursor { created_at: t.created_at, id: t.id.clone() })
+    } else {
+        None
+    };
+    Ok((rows, next))
+}
+
+/// Up to `limit` transfers still `Initiated` or `InTransit`, least recently
+/// updated first.
+pub async fn pending(pool: &SqlitePool, limit: usize) -> Result<Vec<BridgeTransaction>> {
+    let initiated = serde_json::to_string(&BridgeTransactionStatus::Initiated)?;
+    let in_transit = serde_json::to_string(&BridgeTransactionStatus::InTransit)?;
+    sqlx::query("SELECT * FROM bridge_transactions WHERE status IN (?, ?) ORDER BY updated_at, id LIMIT ?")
+        .bind(initiated)
+        .bind(in_transit)
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(from_row)
+        .collect()
+}
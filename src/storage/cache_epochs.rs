@@ -0,0 +1,62 @@
+//! Per-class change counters used to invalidate in-process caches across
+//! instances sharing the database.
+//!
+//! Every mutation of a cached class bumps that class's row inside the
+//! mutation's own transaction, so the counter can never run ahead of (or fall
+//! behind) the data it describes. Readers compare the counter with the value
+//! they last saw; any difference means their cache is stale. Each class has its
+//! own row so unrelated writers never update the same row. The bump adds no
+//! lock of its own: it runs inside a write transaction that already holds the
+//! database write lock, and `SQLITE_BUSY` on that lock is absorbed by the
+//! connection's busy timeout.
+
+use anyhow::Result;
+use sqlx::sqlite::SqlitePool;
+use sqlx::SqliteConnection;
+
+/// Wallet rows: names, metadata, creation state and key material.
+pub const WALLETS: &str = "wallets";
+/// Bridge transfer rows and their status.
+pub const BRIDGE_TRANSACTIONS: &str = "bridge_transactions";
+/// Feature flag rules.
+pub const FEATURE_FLAGS: &str = "feature_flags";
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS cache_epochs (
+            class TEXT PRIMARY KEY,
+            epoch INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Advances the epoch of `class`; call inside the transaction that changes it.
+pub async fn bump(conn: &mut SqliteConnection, class: &str, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO cache_epochs (class, epoch, updated_at) VALUES (?1, 1, ?2)
+        ON CONFLICT (class) DO UPDATE SET epoch = cache_epochs.epoch + 1, updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(class)
+    .bind(now)
+    .execute(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to bump cache epoch of {}: {}", class, e))?;
+    Ok(())
+}
+
+/// Current epoch of `class`; 0 before its first mutation.
+pub async fn read(pool: &SqlitePool, class: &str) -> Result<i64> {
+    let epoch: Option<i64> = sqlx::query_scalar("SELECT epoch FROM cache_epochs WHERE class = ?1")
+        .bind(class)
+        .fetch_optional(pool)
+        .await?;
+    Ok(epoch.unwrap_or(0))
+}
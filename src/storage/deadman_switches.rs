@@ -0,0 +1,285 @@
+//! Dead-man's switch policies.
+//!
+//! One `deadman_policies` row per custodial wallet. The timer runs from
+//! `last_checkin_at`; check-ins only move it while the policy is `armed`.
+//! Warning and trigger transitions are compare-and-set on the check-in time
+//! they were computed from, so a check-in racing the evaluator wins and two
+//! instances never trigger the same period twice. After triggering,
+//! `pending_networks` lists the networks whose sweep has not gone out yet.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+use crate::security::sweep_capability::SweepCapability;
+
+/// Timer running; check-ins reset it
+pub const DEADMAN_ARMED: &str = "armed";
+/// Deadline passed; the sweep was started and the policy no longer accepts check-ins
+pub const DEADMAN_TRIGGERED: &str = "triggered";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadmanPolicy {
+    pub wallet_name: String,
+    pub beneficiary: String,
+    pub networks: Vec<String>,
+    pub inactivity_secs: u64,
+    /// Seconds before the deadline at which a warning is emitted, descending
+    pub warning_secs: Vec<u64>,
+    /// Unix seconds
+    pub last_checkin_at: i64,
+    /// Warnings already emitted for the current period
+    pub warnings_sent: usize,
+    /// [`DEADMAN_ARMED`] or [`DEADMAN_TRIGGERED`]
+    pub status: String,
+    /// Networks still to be swept after triggering
+    pub pending_networks: Vec<String>,
+    pub created_at: i64,
+    pub triggered_at: Option<i64>,
+    #[serde(skip)]
+    pub capability: SweepCapability,
+}
+
+impl DeadmanPolicy {
+    /// Unix seconds at which the switch fires without a check-in
+    pub fn deadline(&self) -> i64 {
+        self.last_checkin_at.saturating_add(self.inactivity_secs as i64)
+    }
+
+    /// How many warnings of the schedule are due at `now`
+    pub fn warnings_due(&self, now: i64) -> usize {
+        let deadline = self.deadline();
+        self.warning_secs.iter().filter(|before| now >= deadline - **before as i64).count()
+    }
+}
+
+/// What gets written when a policy is configured (re-arming resets the timer)
+pub struct NewDeadmanPolicy<'a> {
+    pub wallet_name: &'a str,
+    pub beneficiary: &'a str,
+    pub networks: &'a [String],
+    pub inactivity_secs: u64,
+    pub warning_secs: &'a [u64],
+    pub capability: &'a SweepCapability,
+}
+
+#[derive(FromRow)]
+struct PolicyRow {
+    wallet_name: String,
+    beneficiary: String,
+    networks: String,
+    inactivity_secs: i64,
+    warning_secs: String,
+    last_checkin_at: i64,
+    warnings_sent: i64,
+    status: String,
+    pending_networks: String,
+    capability_salt: Vec<u8>,
+    capability_nonce: Vec<u8>,
+    capability_ciphertext: Vec<u8>,
+    created_at: i64,
+    triggered_at: Option<i64>,
+}
+
+impl PolicyRow {
+    fn into_policy(self) -> Result<DeadmanPolicy> {
+        let corrupt = |field: &str| anyhow::anyhow!("Corrupt {} on dead-man policy of {}", field, self.wallet_name);
+        let networks = serde_json::from_str(&self.networks).map_err(|_| corrupt("networks"))?;
+        let warning_secs = serde_json::from_str(&self.warning_secs).map_err(|_| corrupt("warning_secs"))?;
+        let pending_networks =
+            serde_json::from_str(&self.pending_networks).map_err(|_| corrupt("pending_networks"))?;
+        Ok(DeadmanPolicy {
+            beneficiary: self.beneficiary,
+            networks,
+            inactivity_secs: self.inactivity_secs as u64,
+            warning_secs,
+            last_checkin_at: self.last_checkin_at,
+            warnings_sent: self.warnings_sent as usize,
+            status: self.status,
+            pending_networks,
+            created_at: self.created_at,
+            triggered_at: self.triggered_at,
+            capability: SweepCapability {
+                salt: self.capability_salt,
+                nonce: self.capability_nonce,
+                ciphertext: self.capability_ciphertext,
+            },
+            wallet_name: self.wallet_name,
+        })
+    }
+}
+
+const POLICY_COLUMNS: &str = "wallet_name, beneficiary, networks, inactivity_secs, warning_secs, last_checkin_at, \
+     warnings_sent, status, pending_networks, capability_salt, capability_nonce, capability_ciphertext, \
+     created_at, triggered_at";
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS deadman_policies (
+            wallet_name TEXT PRIMARY KEY,
+            beneficiary TEXT NOT NULL,
+            networks TEXT NOT NULL,
+            inactivity_secs INTEGER NOT NULL,
+            warning_secs TEXT NOT NULL,
+            last_checkin_at INTEGER NOT NULL,
+            warnings_sent INTEGER NOT NULL DEFAULT 0,
+            status TEXT NOT NULL,
+            pending_networks TEXT NOT NULL DEFAULT '[]',
+            capability_salt BLOB NOT NULL,
+            capability_nonce BLOB NOT NULL,
+            capability_ciphertext BLOB NOT NULL,
+            created_at INTEGER NOT NULL,
+            triggered_at INTEGER
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Creates or replaces the wallet's policy, armed with the timer starting at `now`.
+pub async fn upsert(conn: &mut SqliteConnection, policy: &NewDeadmanPolicy<'_>, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO deadman_policies (
+            wallet_name, beneficiary, networks, inactivity_secs, warning_secs, last_checkin_at,
+            warnings_sent, status, pending_networks, capability_salt, capability_nonce,
+            capability_ciphertext, created_at, triggered_at
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7, '[]', ?8, ?9, ?10, ?6, NULL)
+        ON CONFLICT(wallet_name) DO UPDATE SET
+            beneficiary = excluded.beneficiary,
+            networks = excluded.networks,
+            inactivity_secs = excluded.inactivity_secs,
+            warning_secs = excluded.warning_secs,
+            last_checkin_at = excluded.last_checkin_at,
+            warnings_sent = 0,
+            status = excluded.status,
+            pending_networks = '[]',
+            capability_salt = excluded.capability_salt,
+            capability_nonce = excluded.capability_nonce,
+            capability_ciphertext = excluded.capability_ciphertext,
+            triggered_at = NULL
+        "#,
+    )
+    .bind(policy.wallet_name)
+    .bind(policy.beneficiary)
+    .bind(serde_json::to_string(policy.networks)?)
+    .bind(policy.inactivity_secs as i64)
+    .bind(serde_json::to_string(policy.warning_secs)?)
+    .bind(now)
+    .bind(DEADMAN_ARMED)
+    .bind(&policy.capability.salt)
+    .bind(&policy.capability.nonce)
+    .bind(&policy.capability.ciphertext)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store dead-man policy: {}", e))?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, wallet_name: &str) -> Result<Option<DeadmanPolicy>> {
+    sqlx::query_as::<_, PolicyRow>(&format!("SELECT {} FROM deadman_policies WHERE wallet_name = ?1", POLICY_COLUMNS))
+        .bind(wallet_name)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load dead-man policy: {}", e))?
+        .map(PolicyRow::into_policy)
+        .transpose()
+}
+
+/// Policies the evaluator has work for: armed, or triggered with networks left to sweep
+pub async fn list_active(pool: &SqlitePool) -> Result<Vec<DeadmanPolicy>> {
+    sqlx::query_as::<_, PolicyRow>(&format!(
+        "SELECT {} FROM deadman_policies WHERE status = ?1 OR pending_networks != '[]' ORDER BY wallet_name",
+        POLICY_COLUMNS
+    ))
+    .bind(DEADMAN_ARMED)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to list dead-man policies: {}", e))?
+    .into_iter()
+    .map(PolicyRow::into_policy)
+    .collect()
+}
+
+/// Restarts the timer of an armed policy; false when there is none.
+pub async fn checkin(pool: &SqlitePool, wallet_name: &str, now: i64) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE deadman_policies SET last_checkin_at = MAX(last_checkin_at, ?1), warnings_sent = 0 \
+         WHERE wallet_name = ?2 AND status = ?3",
+    )
+    .bind(now)
+    .bind(wallet_name)
+    .bind(DEADMAN_ARMED)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to check in dead-man policy: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Advances `warnings_sent` unless the period moved on (check-in) meanwhile.
+pub async fn record_warning(conn: &mut SqliteConnection, policy: &DeadmanPolicy, warnings_sent: usize) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE deadman_policies SET warnings_sent = ?1 \
+         WHERE wallet_name = ?2 AND status = ?3 AND last_checkin_at = ?4 AND warnings_sent < ?1",
+    )
+    .bind(warnings_sent as i64)
+    .bind(&policy.wallet_name)
+    .bind(DEADMAN_ARMED)
+    .bind(policy.last_checkin_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record dead-man warning: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Armed → triggered with every network pending; false if a check-in or
+/// another instance got there first.
+pub async fn trigger(conn: &mut SqliteConnection, policy: &DeadmanPolicy, now: i64) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE deadman_policies SET status = ?1, triggered_at = ?2, pending_networks = networks \
+         WHERE wallet_name = ?3 AND status = ?4 AND last_checkin_at = ?5",
+    )
+    .bind(DEADMAN_TRIGGERED)
+    .bind(now)
+    .bind(&policy.wallet_name)
+    .bind(DEADMAN_ARMED)
+    .bind(policy.last_checkin_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to trigger dead-man policy: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn set_pending(pool: &SqlitePool, wallet_name: &str, pending: &[String]) -> Result<()> {
+    sqlx::query("UPDATE deadman_policies SET pending_networks = ?1 WHERE wallet_name = ?2 AND status = ?3")
+        .bind(serde_json::to_string(pending)?)
+        .bind(wallet_name)
+        .bind(DEADMAN_TRIGGERED)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update dead-man sweep progress: {}", e))?;
+    Ok(())
+}
+
+pub async fn delete(conn: &mut SqliteConnection, wallet_name: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM deadman_policies WHERE wallet_name = ?1")
+        .bind(wallet_name)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to delete dead-man policy: {}", e))?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// The capability is bound to the beneficiary, not the wallet name, so it survives a rename.
+pub async fn rename(conn: &mut SqliteConnection, wallet_name: &str, new_name: &str) -> Result<()> {
+    sqlx::query("UPDATE deadman_policies SET wallet_name = ?1 WHERE wallet_name = ?2")
+        .bind(new_name)
+        .bind(wallet_name)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,407 @@
+//! Delegated spending sessions.
+//!
+//! A delegation lets an automation agent send from one wallet without the
+//! wallet password, inside limits the owner fixed at creation: a total budget
+//! per network, a per-transaction cap, an optional recipient allowlist and an
+//! expiry. Only the SHA-256 of the `dlg_` token is stored, next to the wallet
+//! signing key sealed under a key that also needs that token.
+//!
+//! Amounts are decimal strings in the network's minimal unit (wei) since they
+//! do not fit SQLite integers. A send reserves its value with
+//! [`reserve`] before signing; the reservation first touches the delegation
+//! row, which takes SQLite's write lock, so the budget read and the
+//! compare-and-set that follows cannot interleave with another send.
+
+use anyhow::Result;
+use ethers::types::U256;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+use crate::security::delegation_key::DelegationKey;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DelegationBudget {
+    pub network: String,
+    pub total: String,
+    pub spent: String,
+    pub remaining: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DelegationRecord {
+    pub id: String,
+    pub wallet_name: String,
+    pub owner_user_id: String,
+    /// Largest single send, in minimal units
+    pub per_tx_cap: String,
+    /// Lowercase addresses; `None` allows any recipient
+    pub recipients: Option<Vec<String>>,
+    pub budgets: Vec<DelegationBudget>,
+    /// Unix seconds
+    pub expires_at: i64,
+    pub created_by: String,
+    pub created_at: i64,
+    pub last_used_at: Option<i64>,
+    pub revoked_at: Option<i64>,
+    #[serde(skip)]
+    pub key: DelegationKey,
+}
+
+impl DelegationRecord {
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+
+    pub fn allows_recipient(&self, address: &str) -> bool {
+        let address = address.to_ascii_lowercase();
+        self.recipients.as_ref().is_none_or(|list| list.contains(&address))
+    }
+
+    pub fn budget(&self, network: &str) -> Option<&DelegationBudget> {
+        self.budgets.iter().find(|b| b.network == network)
+    }
+
+    /// Per-transaction cap as a number; unparsable caps allow nothing
+    pub fn per_tx_cap_wei(&self) -> U256 {
+        U256::from_dec_str(&self.per_tx_cap).unwrap_or_default()
+    }
+}
+
+pub struct NewDelegation<'a> {
+    pub id: &'a str,
+    pub token_hash: &'a str,
+    pub wallet_name: &'a str,
+    pub owner_user_id: &'a str,
+    pub per_tx_cap: U256,
+    pub recipients: Option<&'a [String]>,
+    pub budgets: &'a [(String, U256)],
+    pub expires_at: i64,
+    pub created_by: &'a str,
+    pub key: &'a DelegationKey,
+}
+
+/// Outcome of [`reserve`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reservation {
+    /// Budget on the network and the amount spent including this reservation
+    Reserved { total: U256, spent: U256 },
+    /// Unknown or revoked delegation
+    Revoked,
+    Expired,
+    /// The delegation grants nothing on this network
+    NoBudget,
+    Exhausted { remaining: U256 },
+}
+
+#[derive(FromRow)]
+struct DelegationRow {
+    id: String,
+    wallet_name: String,
+    owner_user_id: String,
+    per_tx_cap: String,
+    recipients: Option<String>,
+    expires_at: i64,
+    created_by: String,
+    created_at: i64,
+    last_used_at: Option<i64>,
+    revoked_at: Option<i64>,
+    key_salt: Vec<u8>,
+    key_nonce: Vec<u8>,
+    key_ciphertext: Vec<u8>,
+}
+
+#[derive(FromRow)]
+struct BudgetRow {
+    network: String,
+    total: String,
+    spent: String,
+}
+
+const DELEGATION_COLUMNS: &str = "id, wallet_name, owner_user_id, per_tx_cap, recipients, expires_at, created_by, \
+     created_at, last_used_at, revoked_at, key_salt, key_nonce, key_ciphertext";
+
+fn parse_amount(value: &str, what: &str) -> Result<U256> {
+    U256::from_dec_str(value).map_err(|_| anyhow::anyhow!("Corrupt {} amount {:?}", what, value))
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS delegations (
+            id TEXT PRIMARY KEY,
+            token_hash TEXT NOT NULL UNIQUE,
+            wallet_name TEXT NOT NULL,
+            owner_user_id TEXT NOT NULL,
+            per_tx_cap TEXT NOT NULL,
+            recipients TEXT,
+            expires_at INTEGER NOT NULL,
+            created_by TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            last_used_at INTEGER,
+            revoked_at INTEGER,
+            key_salt BLOB NOT NULL,
+            key_nonce BLOB NOT NULL,
+            key_ciphertext BLOB NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS delegation_budgets (
+            delegation_id TEXT NOT NULL,
+            network TEXT NOT NULL,
+            total TEXT NOT NULL,
+            spent TEXT NOT NULL DEFAULT '0',
+            PRIMARY KEY (delegation_id, network)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_delegations_wallet ON delegations (wallet_name, created_at)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn insert(conn: &mut SqliteConnection, delegation: &NewDelegation<'_>, now: i64) -> Result<()> {
+    let recipients = delegation.recipients.map(serde_json::to_string).transpose()?;
+    sqlx::query(
+        r#"
+        INSERT INTO delegations (
+            id, token_hash, wallet_name, owner_user_id, per_tx_cap, recipients, expires_at, created_by,
+            created_at, key_salt, key_nonce, key_ciphertext
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+        "#,
+    )
+    .bind(delegation.id)
+    .bind(delegation.token_hash)
+    .bind(delegation.wallet_name)
+    .bind(delegation.owner_user_id)
+    .bind(delegation.per_tx_cap.to_string())
+    .bind(recipients)
+    .bind(delegation.expires_at)
+    .bind(delegation.created_by)
+    .bind(now)
+    .bind(&delegation.key.salt)
+    .bind(&delegation.key.nonce)
+    .bind(&delegation.key.ciphertext)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store delegation: {}", e))?;
+    for (network, total) in delegation.budgets {
+        sqlx::query("INSERT INTO delegation_budgets (delegation_id, network, total, spent) VALUES (?1, ?2, ?3, '0')")
+            .bind(delegation.id)
+            .bind(network)
+            .bind(total.to_string())
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to store delegation budget: {}", e))?;
+    }
+    Ok(())
+}
+
+async fn load(pool: &SqlitePool, row: DelegationRow) -> Result<DelegationRecord> {
+    let budgets = sqlx::query_as::<_, BudgetRow>(
+        "SELECT network, total, spent FROM delegation_budgets WHERE delegation_id = ?1 ORDER BY network",
+    )
+    .bind(&row.id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|b| {
+        let remaining = parse_amount(&b.total, "budget")?.saturating_sub(parse_amount(&b.spent, "spent")?);
+        Ok(DelegationBudget { network: b.network, total: b.total, spent: b.spent, remaining: remaining.to_string() })
+    })
+    .collect::<Result<Vec<_>>>()?;
+    let recipients = row
+        .recipients
+        .as_deref()
+        .map(serde_json::from_str)
+        .transpose()
+        .map_err(|_| anyhow::anyhow!("Corrupt recipients on delegation {}", row.id))?;
+    Ok(DelegationRecord {
+        wallet_name: row.wallet_name,
+        owner_user_id: row.owner_user_id,
+        per_tx_cap: row.per_tx_cap,
+        recipients,
+        budgets,
+        expires_at: row.expires_at,
+        created_by: row.created_by,
+        created_at: row.created_at,
+        last_used_at: row.last_used_at,
+        revoked_at: row.revoked_at,
+        key: DelegationKey { salt: row.key_salt, nonce: row.key_nonce, ciphertext: row.key_ciphertext },
+        id: row.id,
+    })
+}
+
+/// Unrevoked delegation by token hash; may be expired.
+pub async fn find_active(pool: &SqlitePool, token_hash: &str) -> Result<Option<DelegationRecord>> {
+    let row = sqlx::query_as::<_, DelegationRow>(&format!(
+        "SELECT {} FROM delegations WHERE token_hash = ?1 AND revoked_at IS NULL",
+        DELEGATION_COLUMNS
+    ))
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load delegation: {}", e))?;
+    match row {
+        Some(row) => load(pool, row).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Any delegation of the wallet by id, revoked or not.
+pub async fn get(pool: &SqlitePool, wallet_name: &str, id: &str) -> Result<Option<DelegationRecord>> {
+    let row = sqlx::query_as::<_, DelegationRow>(&format!(
+        "SELECT {} FROM delegations WHERE id = ?1 AND wallet_name = ?2",
+        DELEGATION_COLUMNS
+    ))
+    .bind(id)
+    .bind(wallet_name)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load delegation: {}", e))?;
+    match row {
+        Some(row) => load(pool, row).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Newest first, revoked and expired included
+pub async fn list_for_wallet(pool: &SqlitePool, wallet_name: &str) -> Result<Vec<DelegationRecord>> {
+    let rows = sqlx::query_as::<_, DelegationRow>(&format!(
+        "SELECT {} FROM delegations WHERE wallet_name = ?1 ORDER BY created_at DESC, id",
+        DELEGATION_COLUMNS
+    ))
+    .bind(wallet_name)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to list delegations: {}", e))?;
+    let mut delegations = Vec::with_capacity(rows.len());
+    for row in rows {
+        delegations.push(load(pool, row).await?);
+    }
+    Ok(delegations)
+}
+
+/// `false` if no such unrevoked delegation exists on the wallet.
+pub async fn revoke(pool: &SqlitePool, wallet_name: &str, id: &str, now: i64) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE delegations SET revoked_at = ?1 WHERE id = ?2 AND wallet_name = ?3 AND revoked_at IS NULL",
+    )
+    .bind(now)
+    .bind(id)
+    .bind(wallet_name)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to revoke delegation: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Adds `amount` to the network's spent total if the delegation is live and
+/// the budget covers it. Call inside a transaction.
+pub async fn reserve(
+    conn: &mut SqliteConnection,
+    id: &str,
+    network: &str,
+    amount: U256,
+    now: i64,
+) -> Result<Reservation> {
+    // Writing first takes the database write lock for the rest of the transaction
+    let touched = sqlx::query("UPDATE delegations SET last_used_at = ?1 WHERE id = ?2 AND revoked_at IS NULL")
+        .bind(now)
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to reserve delegation budget: {}", e))?;
+    if touched.rows_affected() == 0 {
+        return Ok(Reservation::Revoked);
+    }
+    let expires_at: i64 = sqlx::query_scalar("SELECT expires_at FROM delegations WHERE id = ?1")
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if now >= expires_at {
+        return Ok(Reservation::Expired);
+    }
+    let Some(budget) = sqlx::query_as::<_, BudgetRow>(
+        "SELECT network, total, spent FROM delegation_budgets WHERE delegation_id = ?1 AND network = ?2",
+    )
+    .bind(id)
+    .bind(network)
+    .fetch_optional(&mut *conn)
+    .await?
+    else {
+        return Ok(Reservation::NoBudget);
+    };
+
+    let total = parse_amount(&budget.total, "budget")?;
+    let spent = parse_amount(&budget.spent, "spent")?;
+    let remaining = total.saturating_sub(spent);
+    if amount > remaining {
+        return Ok(Reservation::Exhausted { remaining });
+    }
+    let updated = sqlx::query(
+        "UPDATE delegation_budgets SET spent = ?1 WHERE delegation_id = ?2 AND network = ?3 AND spent = ?4",
+    )
+    .bind((spent + amount).to_string())
+    .bind(id)
+    .bind(network)
+    .bind(&budget.spent)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to reserve delegation budget: {}", e))?;
+    if updated.rows_affected() != 1 {
+        anyhow::bail!("Delegation {} budget on {} changed during reservation", id, network);
+    }
+    Ok(Reservation::Reserved { total, spent: spent + amount })
+}
+
+/// Returns a reservation whose send never reached the network.
+pub async fn release(conn: &mut SqliteConnection, id: &str, network: &str, amount: U256) -> Result<()> {
+    let spent: String =
+        sqlx::query_scalar("SELECT spent FROM delegation_budgets WHERE delegation_id = ?1 AND network = ?2")
+            .bind(id)
+            .bind(network)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to release delegation budget: {}", e))?;
+    let released = parse_amount(&spent, "spent")?.saturating_sub(amount);
+    sqlx::query("UPDATE delegation_budgets SET spent = ?1 WHERE delegation_id = ?2 AND network = ?3 AND spent = ?4")
+        .bind(released.to_string())
+        .bind(id)
+        .bind(network)
+        .bind(&spent)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to release delegation budget: {}", e))?;
+    Ok(())
+}
+
+/// A deleted wallet takes its delegations with it; a recreated wallet of the
+/// same name must not inherit them.
+pub async fn delete_for_wallet(conn: &mut SqliteConnection, wallet_name: &str) -> Result<()> {
+    sqlx::query(
+        "DELETE FROM delegation_budgets WHERE delegation_id IN (SELECT id FROM delegations WHERE wallet_name = ?1)",
+    )
+    .bind(wallet_name)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query("DELETE FROM delegations WHERE wallet_name = ?1").bind(wallet_name).execute(&mut *conn).await?;
+    Ok(())
+}
+
+/// The sealed key is bound to the delegation id, so delegations follow a rename.
+pub async fn rename(conn: &mut SqliteConnection, wallet_name: &str, new_name: &str) -> Result<()> {
+    sqlx::query("UPDATE delegations SET wallet_name = ?1 WHERE wallet_name = ?2")
+        .bind(new_name)
+        .bind(wallet_name)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! Lease-based locks shared by every instance using this database.
+//!
+//! A lock is a row `(name, holder_id, expires_at)`. Acquiring is a single
+//! conditional upsert that only succeeds when the row is absent, expired, or
+//! already ours, so two instances racing for the same name get exactly one
+//! winner without any database-specific locking. A crashed holder never
+//! releases; its lease simply runs out and the next caller takes over.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct LockRecord {
+    pub name: String,
+    pub holder_id: String,
+    /// Unix milliseconds
+    pub expires_at: i64,
+    /// Unix milliseconds; kept across renewals by the same holder
+    pub acquired_at: i64,
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn ttl_ms(ttl: Duration) -> i64 {
+    i64::try_from(ttl.as_millis()).unwrap_or(i64::MAX)
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS distributed_locks (
+            name TEXT PRIMARY KEY,
+            holder_id TEXT NOT NULL,
+            expires_at INTEGER NOT NULL,
+            acquired_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Takes `name` for `ttl` if it is free, expired or already held by `holder`.
+pub async fn try_acquire(pool: &SqlitePool, name: &str, holder: &str, ttl: Duration) -> Result<bool> {
+    let now = now_ms();
+    let result = sqlx::query(
+        r#"
+        INSERT INTO distributed_locks (name, holder_id, expires_at, acquired_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(name) DO UPDATE SET
+            holder_id = excluded.holder_id,
+            expires_at = excluded.expires_at,
+            acquired_at = CASE WHEN distributed_locks.holder_id = excluded.holder_id
+                               THEN distributed_locks.acquired_at ELSE excluded.acquired_at END
+        WHERE distributed_locks.holder_id = excluded.holder_id
+           OR distributed_locks.expires_at <= excluded.acquired_at
+        "#,
+    )
+    .bind(name)
+    .bind(holder)
+    .bind(now.saturating_add(ttl_ms(ttl)))
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to acquire lock {}: {}", name, e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Extends a lease `holder` still owns; `false` once it has expired or been taken over.
+pub async fn renew(pool: &SqlitePool, name: &str, holder: &str, ttl: Duration) -> Result<bool> {
+    let now = now_ms();
+    let result = sqlx::query(
+        "UPDATE distributed_locks SET expires_at = ?1 \
+         WHERE name = ?2 AND holder_id = ?3 AND expires_at > ?4",
+    )
+    .bind(now.saturating_add(ttl_ms(ttl)))
+    .bind(name)
+    .bind(holder)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to renew lock {}: {}", name, e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Drops the lock if `holder` owns it.
+pub async fn release(pool: &SqlitePool, name: &str, holder: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM distributed_locks WHERE name = ?1 AND holder_id = ?2")
+        .bind(name)
+        .bind(holder)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to release lock {}: {}", name, e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Locks whose lease has not run out, by name.
+pub async fn active(pool: &SqlitePool) -> Result<Vec<LockRecord>> {
+    Ok(sqlx::query_as::<_, LockRecord>(
+        "SELECT name, holder_id, expires_at, acquired_at FROM distributed_locks \
+         WHERE expires_at > ?1 ORDER BY name",
+    )
+    .bind(now_ms())
+    .fetch_all(pool)
+    .await?)
+}
@@ -0,0 +1,191 @@
+//! Wallet-database side of user erasure, plus the erasure certificates.
+//!
+//! Audit rows keep their action and details; only the network identifiers
+//! (`ip_address`, `user_agent`) are replaced with [`ERASED_MARKER`], and the row's
+//! MAC is recomputed over the redacted content, with the rows after it
+//! relinked to the new MAC, so `get_audit_logs` keeps verifying. Rows already
+//! carrying the marker are left alone, which makes a repeated erasure a no-op
+//! here.
+
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow, QueryBuilder, Sqlite, SqliteConnection};
+
+use super::audit_chain;
+
+/// Replaces personal values in erased rows
+pub const ERASED_MARKER: &str = "[erased]";
+
+/// Rows changed on the wallet database by one erasure run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WalletErasure {
+    /// Ids of the audit rows that were redacted and re-MACed
+    pub redacted_audit_ids: Vec<i64>,
+    pub api_tokens_deleted: u64,
+    /// Groups and balance subscriptions handed to the erased owner
+    pub ownership_transferred: u64,
+}
+
+/// Proof that a user's records were erased
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErasureCertificate {
+    pub id: String,
+    pub user_id: String,
+    /// Operator that requested the erasure
+    pub erased_by: String,
+    /// Records removed or anonymized, by category
+    pub categories: BTreeMap<String, u64>,
+    /// Wallet names handed to the erased owner
+    pub wallets: Vec<String>,
+    /// Unix seconds
+    pub created_at: i64,
+}
+
+#[derive(FromRow)]
+struct CertificateRow {
+    id: String,
+    user_id: String,
+    erased_by: String,
+    categories: String,
+    wallets: String,
+    created_at: i64,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS erasure_certificates (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            erased_by TEXT NOT NULL,
+            categories TEXT NOT NULL,
+            wallets TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_erasure_certificates_user ON erasure_certificates (user_id)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Redacts the network identifiers of audit rows whose `wallet_id` is one of
+/// `wallet_keys` and re-MACs them, relinking the audit chain behind them.
+/// Returns the ids of the rows changed.
+pub async fn redact_audit_rows(conn: &mut SqliteConnection, wallet_keys: &[String]) -> Result<Vec<i64>> {
+    if wallet_keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+        "SELECT id FROM audit_logs WHERE wallet_id IN (",
+    );
+    let mut sep = qb.separated(", ");
+    for key in wallet_keys {
+        sep.push_bind(key.as_str());
+    }
+    sep.push_unseparated(")");
+    qb.push(" AND ((ip_address IS NOT NULL AND ip_address <> ");
+    qb.push_bind(ERASED_MARKER);
+    qb.push(") OR (user_agent IS NOT NULL AND user_agent <> ");
+    qb.push_bind(ERASED_MARKER);
+    qb.push(")) ORDER BY id");
+    let ids: Vec<i64> = qb
+        .build_query_scalar()
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to select audit rows for erasure: {}", e))?;
+
+    let ids: HashSet<i64> = ids.into_iter().collect();
+    audit_chain::rewrite(conn, &ids, |row| {
+        row.ip_address = row.ip_address.as_ref().map(|_| ERASED_MARKER.to_string());
+        row.user_agent = row.user_agent.as_ref().map(|_| ERASED_MARKER.to_string());
+        true
+    })
+    .await
+}
+
+/// Deletes every wallet token (bearer or signing key) the user owns, with its
+/// `api_keys` row.
+pub async fn delete_user_tokens(conn: &mut SqliteConnection, user_id: &str) -> Result<u64> {
+    sqlx::query("DELETE FROM api_keys WHERE key_id IN (SELECT id FROM wallet_tokens WHERE owner_user_id = ?1)")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to delete API keys: {}", e))?;
+    let result = sqlx::query("DELETE FROM wallet_tokens WHERE owner_user_id = ?1")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to delete wallet tokens: {}", e))?;
+    Ok(result.rows_affected())
+}
+
+/// Moves ownership of the user's wallet groups and balance subscriptions to
+/// `new_owner`; `created_by` is rewritten where it names the user.
+pub async fn transfer_ownership(conn: &mut SqliteConnection, user_id: &str, new_owner: &str) -> Result<u64> {
+    let mut moved = 0;
+    for table in ["wallet_groups", "balance_subscriptions"] {
+        let result = sqlx::query(&format!(
+            "UPDATE {} SET owner_user_id = ?1, \
+             created_by = CASE WHEN created_by = ?2 THEN ?1 ELSE created_by END \
+             WHERE owner_user_id = ?2",
+            table
+        ))
+        .bind(new_owner)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to transfer {} ownership: {}", table, e))?;
+        moved += result.rows_affected();
+    }
+    Ok(moved)
+}
+
+pub async fn insert_certificate(conn: &mut SqliteConnection, cert: &ErasureCertificate) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO erasure_certificates (id, user_id, erased_by, categories, wallets, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        "#,
+    )
+    .bind(&cert.id)
+    .bind(&cert.user_id)
+    .bind(&cert.erased_by)
+    .bind(serde_json::to_string(&cert.categories)?)
+    .bind(serde_json::to_string(&cert.wallets)?)
+    .bind(cert.created_at)
+    .execute(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store erasure certificate: {}", e))?;
+    Ok(())
+}
+
+/// Certificates issued for `user_id`, oldest first.
+pub async fn list_for_user(pool: &SqlitePool, user_id: &str) -> Result<Vec<ErasureCertificate>> {
+    let rows = sqlx::query_as::<_, CertificateRow>(
+        "SELECT id, user_id, erased_by, categories, wallets, created_at FROM erasure_certificates \
+         WHERE user_id = ?1 ORDER BY created_at, rowid",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to read erasure certificates: {}", e))?;
+
+    rows.into_iter()
+        .map(|r| {
+            Ok(ErasureCertificate {
+                id: r.id,
+                user_id: r.user_id,
+                erased_by: r.erased_by,
+                categories: serde_json::from_str(&r.categories)?,
+                wallets: serde_json::from_str(&r.wallets)?,
+                created_at: r.created_at,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,273 @@
+//! Token behavior overrides and delivery checks of ERC-20 sends.
+//!
+//! `token_behaviors` holds the flags set through the admin API; they take
+//! precedence over the `tokens` config section. `token_deliveries` has one
+//! row per token transfer sent with `verify_delivery`, settled when its
+//! receipt arrives with the amount the recipient actually received.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+use crate::blockchain::erc20::TokenBehavior;
+
+/// [`TokenDeliveryRecord::status`] until the receipt is in
+pub const DELIVERY_PENDING: &str = "pending";
+/// The recipient received the full amount
+pub const DELIVERY_DELIVERED: &str = "delivered";
+/// The recipient received less than was sent (fee-on-transfer)
+pub const DELIVERY_SHORT: &str = "short";
+/// The transaction succeeded but no transfer to the recipient was logged
+pub const DELIVERY_UNDELIVERED: &str = "undelivered";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct TokenBehaviorRecord {
+    pub network: String,
+    /// Lowercase contract address
+    pub token: String,
+    pub no_bool_return: bool,
+    pub fee_on_transfer: bool,
+    pub max_approval_disallowed: bool,
+    pub verify_delivery: bool,
+    pub updated_by: String,
+    pub updated_at: i64,
+}
+
+impl TokenBehaviorRecord {
+    pub fn behavior(&self) -> TokenBehavior {
+        TokenBehavior {
+            no_bool_return: self.no_bool_return,
+            fee_on_transfer: self.fee_on_transfer,
+            max_approval_disallowed: self.max_approval_disallowed,
+            verify_delivery: self.verify_delivery,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct TokenDeliveryRecord {
+    pub network: String,
+    pub tx_hash: String,
+    pub wallet_name: String,
+    pub token: String,
+    pub recipient: String,
+    /// Base units, decimal
+    pub sent: String,
+    /// Base units, decimal; `None` while pending
+    pub delivered: Option<String>,
+    /// Whether the token was flagged fee-on-transfer when it was sent
+    pub fee_on_transfer: bool,
+    pub status: String,
+    pub created_at: i64,
+    pub settled_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NewTokenDelivery<'a> {
+    pub network: &'a str,
+    pub tx_hash: &'a str,
+    pub wallet_name: &'a str,
+    pub token: &'a str,
+    pub recipient: &'a str,
+    pub sent: &'a str,
+    pub fee_on_transfer: bool,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS token_behaviors (
+            network TEXT NOT NULL,
+            token TEXT NOT NULL,
+            no_bool_return INTEGER NOT NULL DEFAULT 0,
+            fee_on_transfer INTEGER NOT NULL DEFAULT 0,
+            max_approval_disallowed INTEGER NOT NULL DEFAULT 0,
+            verify_delivery INTEGER NOT NULL DEFAULT 0,
+            updated_by TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (network, token)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS token_deliveries (
+            network TEXT NOT NULL,
+            tx_hash TEXT NOT NULL,
+            wallet_name TEXT NOT NULL,
+            token TEXT NOT NULL,
+            recipient TEXT NOT NULL,
+            sent TEXT NOT NULL,
+            delivered TEXT,
+            fee_on_transfer INTEGER NOT NULL DEFAULT 0,
+            status TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            settled_at INTEGER,
+            PRIMARY KEY (network, tx_hash)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_token_deliveries_wallet ON token_deliveries (wallet_name, status)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+const BEHAVIOR_COLUMNS: &str =
+    "network, token, no_bool_return, fee_on_transfer, max_approval_disallowed, verify_delivery, updated_by, updated_at";
+
+const DELIVERY_COLUMNS: &str = "network, tx_hash, wallet_name, token, recipient, sent, delivered, fee_on_transfer, \
+     status, created_at, settled_at";
+
+/// Every stored override, by network and token
+pub async fn list_behaviors(pool: &SqlitePool) -> Result<Vec<TokenBehaviorRecord>> {
+    let rows = sqlx::query_as(&format!("SELECT {} FROM token_behaviors ORDER BY network, token", BEHAVIOR_COLUMNS))
+        .fetch_all(pool)
+        .await?;
+    Ok(rows)
+}
+
+pub async fn get_behavior(pool: &SqlitePool, network: &str, token: &str) -> Result<Option<TokenBehaviorRecord>> {
+    let row =
+        sqlx::query_as(&format!("SELECT {} FROM token_behaviors WHERE network = ?1 AND token = ?2", BEHAVIOR_COLUMNS))
+            .bind(network)
+            .bind(token.to_lowercase())
+            .fetch_optional(pool)
+            .await?;
+    Ok(row)
+}
+
+/// Inserts or replaces the override of `token` on `network`.
+pub async fn put_behavior(
+    pool: &SqlitePool,
+    network: &str,
+    token: &str,
+    behavior: &TokenBehavior,
+    updated_by: &str,
+    now: i64,
+) -> Result<TokenBehaviorRecord> {
+    let record = sqlx::query_as(&format!(
+        r#"
+        INSERT INTO token_behaviors (network, token, no_bool_return, fee_on_transfer, max_approval_disallowed,
+                                     verify_delivery, updated_by, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        ON CONFLICT (network, token) DO UPDATE SET
+            no_bool_return = excluded.no_bool_return,
+            fee_on_transfer = excluded.fee_on_transfer,
+            max_approval_disallowed = excluded.max_approval_disallowed,
+            verify_delivery = excluded.verify_delivery,
+            updated_by = excluded.updated_by,
+            updated_at = excluded.updated_at
+        RETURNING {}
+        "#,
+        BEHAVIOR_COLUMNS
+    ))
+    .bind(network)
+    .bind(token.to_lowercase())
+    .bind(behavior.no_bool_return)
+    .bind(behavior.fee_on_transfer)
+    .bind(behavior.max_approval_disallowed)
+    .bind(behavior.verify_delivery)
+    .bind(updated_by)
+    .bind(now)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store token behavior of {}: {}", token, e))?;
+    Ok(record)
+}
+
+/// Drops the override of `token` on `network`; `false` if there was none.
+pub async fn remove_behavior(pool: &SqlitePool, network: &str, token: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM token_behaviors WHERE network = ?1 AND token = ?2")
+        .bind(network)
+        .bind(token.to_lowercase())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Registers a sent transfer to be checked; a hash already registered is
+/// left alone.
+pub async fn insert_delivery(pool: &SqlitePool, delivery: &NewTokenDelivery<'_>, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO token_deliveries (network, tx_hash, wallet_name, token, recipient, sent, fee_on_transfer,
+                                      status, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        ON CONFLICT (network, tx_hash) DO NOTHING
+        "#,
+    )
+    .bind(delivery.network)
+    .bind(delivery.tx_hash.to_lowercase())
+    .bind(delivery.wallet_name)
+    .bind(delivery.token.to_lowercase())
+    .bind(delivery.recipient.to_lowercase())
+    .bind(delivery.sent)
+    .bind(delivery.fee_on_transfer)
+    .bind(DELIVERY_PENDING)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to register token delivery {}: {}", delivery.tx_hash, e))?;
+    Ok(())
+}
+
+pub async fn delivery(pool: &SqlitePool, network: &str, tx_hash: &str) -> Result<Option<TokenDeliveryRecord>> {
+    let row = sqlx::query_as(&format!(
+        "SELECT {} FROM token_deliveries WHERE network = ?1 AND tx_hash = ?2",
+        DELIVERY_COLUMNS
+    ))
+    .bind(network)
+    .bind(tx_hash.to_lowercase())
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Records what arrived; only a pending delivery is settled, so a receipt
+/// applied twice changes nothing. Returns whether the row was settled.
+pub async fn settle(
+    pool: &SqlitePool,
+    network: &str,
+    tx_hash: &str,
+    delivered: &str,
+    status: &str,
+    now: i64,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE token_deliveries SET delivered = ?1, status = ?2, settled_at = ?3
+        WHERE network = ?4 AND tx_hash = ?5 AND status = ?6
+        "#,
+    )
+    .bind(delivered)
+    .bind(status)
+    .bind(now)
+    .bind(network)
+    .bind(tx_hash.to_lowercase())
+    .bind(DELIVERY_PENDING)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to settle token delivery {}: {}", tx_hash, e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Moves the deliveries of a renamed wallet.
+pub async fn rename(conn: &mut SqliteConnection, wallet_name: &str, new_name: &str) -> Result<()> {
+    sqlx::query("UPDATE token_deliveries SET wallet_name = ?1 WHERE wallet_name = ?2")
+        .bind(new_name)
+        .bind(wallet_name)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Drops the deliveries of a deleted wallet.
+pub async fn delete_for_wallet(conn: &mut SqliteConnection, wallet_name: &str) -> Result<()> {
+    sqlx::query("DELETE FROM token_deliveries WHERE wallet_name = ?1").bind(wallet_name).execute(conn).await?;
+    Ok(())
+}
@@ -0,0 +1,151 @@
+//! Ordered journal of state changes for external consumers.
+//!
+//! Rows are appended inside the same database transaction as the change they
+//! describe, so an event exists if and only if the change committed. SQLite
+//! admits one writer at a time and `AUTOINCREMENT` allocation rolls back with
+//! the transaction, which makes the committed `seq` values gapless and
+//! strictly increasing in commit order. Consumers read with
+//! `seq > after_seq` and advance their cursor after processing.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow, Row, SqliteConnection};
+
+use crate::security::redaction::redact_json;
+
+pub const WALLET_CREATED: &str = "wallet.created";
+pub const WALLET_DELETED: &str = "wallet.deleted";
+pub const WALLET_RENAMED: &str = "wallet.renamed";
+pub const TRANSACTION_CREATED: &str = "transaction.created";
+/// Limits, policies and rule-set hash a server-signed transaction was sent under
+pub const TRANSACTION_DECIDED: &str = "transaction.decided";
+pub const TRANSACTION_STATUS_CHANGED: &str = "transaction.status_changed";
+pub const TRANSACTION_EXPIRED: &str = "transaction.expired";
+pub const LEDGER_CORRECTED: &str = "ledger.corrected";
+pub const BRIDGE_STATUS_CHANGED: &str = "bridge.status_changed";
+pub const LIMITS_CHANGED: &str = "limits.changed";
+pub const KEY_ROTATED: &str = "key.rotated";
+pub const SECURITY_EVENT: &str = "security.event";
+pub const APPROVAL_REQUESTED: &str = "approval.requested";
+pub const APPROVAL_STATUS_CHANGED: &str = "approval.status_changed";
+pub const BALANCE_CHANGED: &str = "balance.changed";
+pub const DEADMAN_SWITCH_WARNING: &str = "deadman.warning";
+pub const DEADMAN_SWITCH_TRIGGERED: &str = "deadman.triggered";
+pub const AUDIT_REMACED: &str = "audit.remaced";
+pub const USER_ERASED: &str = "user.erased";
+pub const BUNDLE_CREATED: &str = "bundle.created";
+pub const BUNDLE_STATUS_CHANGED: &str = "bundle.status_changed";
+pub const BUNDLE_STEP_STATUS_CHANGED: &str = "bundle.step_status_changed";
+pub const FEATURE_FLAG_CHANGED: &str = "feature_flag.changed";
+/// A request answered with a 5xx; the payload carries the error class, not the message
+pub const REQUEST_FAILED: &str = "request.failed";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEvent {
+    pub seq: i64,
+    pub event_type: String,
+    /// `wallet`, `transaction`, `bridge_transaction`, `signing_key`, ...
+    pub entity_type: String,
+    pub entity_id: String,
+    pub payload: serde_json::Value,
+    /// Unix seconds
+    pub created_at: i64,
+}
+
+/// An event to append; `payload` is redacted before it is written.
+#[derive(Debug, Clone)]
+pub struct NewJournalEvent<'a> {
+    pub event_type: &'a str,
+    pub entity_type: &'a str,
+    pub entity_id: &'a str,
+    pub payload: serde_json::Value,
+}
+
+#[derive(FromRow)]
+struct JournalRow {
+    seq: i64,
+    event_type: String,
+    entity_type: String,
+    entity_id: String,
+    payload: String,
+    created_at: i64,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS events_journal (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            event_type TEXT NOT NULL,
+            entity_type TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to create events_journal table: {}", e))?;
+    Ok(())
+}
+
+/// Appends `event` on `conn`, which must be inside the transaction making
+/// the change. Returns the assigned sequence number.
+/// `now`: Unix seconds, from the storage clock
+pub async fn append(conn: &mut SqliteConnection, event: &NewJournalEvent<'_>, now: i64) -> Result<i64> {
+    let mut payload = event.payload.clone();
+    redact_json(&mut payload);
+    let result = sqlx::query(
+        r#"
+        INSERT INTO events_journal (event_type, entity_type, entity_id, payload, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        "#,
+    )
+    .bind(event.event_type)
+    .bind(event.entity_type)
+    .bind(event.entity_id)
+    .bind(payload.to_string())
+    .bind(now)
+    .execute(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to append journal event: {}", e))?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Up to `limit` events with `seq > after_seq`, in sequence order.
+pub async fn list_after(pool: &SqlitePool, after_seq: i64, limit: i64) -> Result<Vec<JournalEvent>> {
+    let rows = sqlx::query_as::<_, JournalRow>(
+        r#"
+        SELECT seq, event_type, entity_type, entity_id, payload, created_at
+        FROM events_journal
+        WHERE seq > ?1
+        ORDER BY seq ASC
+        LIMIT ?2
+        "#,
+    )
+    .bind(after_seq)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to read events journal: {}", e))?;
+
+    rows.into_iter()
+        .map(|r| {
+            Ok(JournalEvent {
+                seq: r.seq,
+                event_type: r.event_type,
+                entity_type: r.entity_type,
+                entity_id: r.entity_id,
+                payload: serde_json::from_str(&r.payload)?,
+                created_at: r.created_at,
+            })
+        })
+        .collect()
+}
+
+/// Highest committed sequence number (0 when the journal is empty).
+pub async fn last_seq(pool: &SqlitePool) -> Result<i64> {
+    let row = sqlx::query("SELECT COALESCE(MAX(seq), 0) AS seq FROM events_journal").fetch_one(pool).await?;
+    Ok(row.get("seq"))
+}
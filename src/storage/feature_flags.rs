@@ -0,0 +1,122 @@
+//! Stored feature flag rules.
+//!
+//! One row per (flag, scope): the global rule has an empty `wallet_id`, so
+//! the primary key also keeps it unique (SQLite treats NULLs in a key as
+//! distinct). Which flag names exist is the caller's concern; this table
+//! stores whatever it is given. Every change bumps the `feature_flags` cache
+//! epoch in its own transaction.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+/// Scope column value of the global rule
+const GLOBAL_SCOPE: &str = "";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct FeatureFlagRecord {
+    pub flag: String,
+    /// `None` for the global rule
+    pub wallet_id: Option<String>,
+    pub enabled: bool,
+    /// Share of wallets (0-100) the global rule is enabled for; `None` is all
+    pub rollout_percent: Option<i64>,
+    pub updated_by: String,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NewFeatureFlag<'a> {
+    pub flag: &'a str,
+    pub wallet_id: Option<&'a str>,
+    pub enabled: bool,
+    pub rollout_percent: Option<u8>,
+    pub updated_by: &'a str,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS feature_flags (
+            flag TEXT NOT NULL,
+            wallet_id TEXT NOT NULL DEFAULT '',
+            enabled INTEGER NOT NULL,
+            rollout_percent INTEGER,
+            updated_by TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (flag, wallet_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+const COLUMNS: &str =
+    "flag, NULLIF(wallet_id, '') AS wallet_id, enabled, rollout_percent, updated_by, updated_at";
+
+/// Every rule, global rules first within each flag
+pub async fn list(pool: &SqlitePool) -> Result<Vec<FeatureFlagRecord>> {
+    let rows = sqlx::query_as(&format!("SELECT {} FROM feature_flags ORDER BY flag, wallet_id", COLUMNS))
+        .fetch_all(pool)
+        .await?;
+    Ok(rows)
+}
+
+/// Inserts or replaces the rule of `rule.flag` for its scope.
+pub async fn put(conn: &mut SqliteConnection, rule: &NewFeatureFlag<'_>, now: i64) -> Result<FeatureFlagRecord> {
+    let record = sqlx::query_as(&format!(
+        r#"
+        INSERT INTO feature_flags (flag, wallet_id, enabled, rollout_percent, updated_by, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ON CONFLICT (flag, wallet_id) DO UPDATE SET
+            enabled = excluded.enabled,
+            rollout_percent = excluded.rollout_percent,
+            updated_by = excluded.updated_by,
+            updated_at = excluded.updated_at
+        RETURNING {}
+        "#,
+        COLUMNS
+    ))
+    .bind(rule.flag)
+    .bind(rule.wallet_id.unwrap_or(GLOBAL_SCOPE))
+    .bind(rule.enabled)
+    .bind(rule.rollout_percent.map(i64::from))
+    .bind(rule.updated_by)
+    .bind(now)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store feature flag {}: {}", rule.flag, e))?;
+    Ok(record)
+}
+
+/// Drops the rule of `flag` for `wallet_id` (or the global one); `false` if
+/// there was none.
+pub async fn remove(conn: &mut SqliteConnection, flag: &str, wallet_id: Option<&str>) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM feature_flags WHERE flag = ?1 AND wallet_id = ?2")
+        .bind(flag)
+        .bind(wallet_id.unwrap_or(GLOBAL_SCOPE))
+        .execute(conn)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Drops the rules scoped to a deleted wallet; `true` if there were any.
+pub async fn delete_for_wallet(conn: &mut SqliteConnection, wallet_id: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM feature_flags WHERE wallet_id = ?1 AND wallet_id != ''")
+        .bind(wallet_id)
+        .execute(conn)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Moves the rules of a renamed wallet; `true` if there were any.
+pub async fn rename(conn: &mut SqliteConnection, wallet_id: &str, new_wallet_id: &str) -> Result<bool> {
+    let result = sqlx::query("UPDATE feature_flags SET wallet_id = ?1 WHERE wallet_id = ?2 AND wallet_id != ''")
+        .bind(new_wallet_id)
+        .bind(wallet_id)
+        .execute(conn)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,409 @@
+//! Fee history: what each send offered and paid for gas, plus the gas
+//! oracle's market samples for comparison.
+//!
+//! A row is written at broadcast with the offered caps and completed from the
+//! receipt once the transaction is mined. Gas prices are wei and fit in an
+//! INTEGER; fees paid can overflow i64, so they are decimal TEXT and summed
+//! as u128 in Rust.
+
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow, Row, SqliteConnection};
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS fee_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            network TEXT NOT NULL,
+            tx_hash TEXT NOT NULL,
+            gas_limit INTEGER NOT NULL,
+            gas_used INTEGER,
+            gas_price INTEGER,
+            max_fee_per_gas INTEGER,
+            max_priority_fee_per_gas INTEGER,
+            effective_gas_price INTEGER,
+            fee_paid_wei TEXT,
+            speed TEXT,
+            created_at INTEGER NOT NULL,
+            confirmed_at INTEGER,
+            UNIQUE (network, tx_hash)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_fee_history_network_created ON fee_history (network, created_at)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS gas_price_samples (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            network TEXT NOT NULL,
+            gas_price INTEGER NOT NULL,
+            sampled_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_gas_price_samples_network ON gas_price_samples (network, sampled_at)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Fee data known at broadcast
+#[derive(Debug, Clone, Default)]
+pub struct NewFeeRecord<'a> {
+    pub network: &'a str,
+    pub tx_hash: &'a str,
+    pub gas_limit: u64,
+    /// Legacy / EIP-2930 gas price (wei)
+    pub gas_price: Option<u64>,
+    /// EIP-1559 caps (wei)
+    pub max_fee_per_gas: Option<u64>,
+    pub max_priority_fee_per_gas: Option<u64>,
+    /// Fee speed the send used
+    pub speed: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct FeeRecord {
+    pub network: String,
+    pub tx_hash: String,
+    pub gas_limit: i64,
+    pub gas_used: Option<i64>,
+    pub gas_price: Option<i64>,
+    pub max_fee_per_gas: Option<i64>,
+    pub max_priority_fee_per_gas: Option<i64>,
+    pub effective_gas_price: Option<i64>,
+    pub fee_paid_wei: Option<String>,
+    pub speed: Option<String>,
+    /// Unix seconds
+    pub created_at: i64,
+    pub confirmed_at: Option<i64>,
+}
+
+/// `group_by` of the fee analytics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeGrouping {
+    Hour,
+    #[default]
+    Day,
+    /// Weeks start on Monday 00:00 UTC
+    Week,
+}
+
+impl FeeGrouping {
+    /// SQL expression mapping `created_at` to its bucket start
+    fn bucket_sql(self) -> &'static str {
+        match self {
+            FeeGrouping::Hour => "(created_at / 3600) * 3600",
+            FeeGrouping::Day => "(created_at / 86400) * 86400",
+            // 1970-01-05 (345600) was a Monday
+            FeeGrouping::Week => "((created_at - 345600) / 604800) * 604800 + 345600",
+        }
+    }
+}
+
+/// Aggregates over one bucket (or the whole window)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FeeSummary {
+    /// `None` for the window total
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bucket_start: Option<DateTime<Utc>>,
+    pub transactions: u64,
+    /// Sends with a receipt; only these carry gas used and fee paid
+    pub confirmed: u64,
+    pub total_gas_used: u64,
+    /// Decimal wei
+    pub total_fee_wei: String,
+    pub avg_fee_wei: Option<String>,
+    pub avg_effective_gas_price: Option<u64>,
+    pub p50_effective_gas_price: Option<u64>,
+    pub p95_effective_gas_price: Option<u64>,
+    /// `(max_fee_per_gas - effective_gas_price) * gas_used` over confirmed EIP-1559 sends
+    pub overpayment_wei: String,
+    /// Mean gas oracle sample in the same period
+    pub market_avg_gas_price: Option<u64>,
+}
+
+/// Headroom left unused by an EIP-1559 send (wei).
+pub fn overpayment_wei(max_fee_per_gas: u64, effective_gas_price: u64, gas_used: u64) -> u128 {
+    u128::from(max_fee_per_gas.saturating_sub(effective_gas_price)) * u128::from(gas_used)
+}
+
+/// Nearest-rank percentile of an ascending slice.
+fn percentile(sorted: &[u64], pct: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+pub async fn insert(pool: &SqlitePool, record: &NewFeeRecord<'_>, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO fee_history
+            (network, tx_hash, gas_limit, gas_price, max_fee_per_gas, max_priority_fee_per_gas, speed, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        "#,
+    )
+    .bind(record.network)
+    .bind(record.tx_hash)
+    .bind(record.gas_limit as i64)
+    .bind(record.gas_price.map(|v| v as i64))
+    .bind(record.max_fee_per_gas.map(|v| v as i64))
+    .bind(record.max_priority_fee_per_gas.map(|v| v as i64))
+    .bind(record.speed)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record fee history: {}", e))?;
+    Ok(())
+}
+
+/// Fills in receipt data; returns whether a pending row was completed.
+pub async fn complete(
+    pool: &SqlitePool,
+    network: &str,
+    tx_hash: &str,
+    gas_used: u64,
+    effective_gas_price: u64,
+    now: i64,
+) -> Result<bool> {
+    let fee_paid = u128::from(gas_used) * u128::from(effective_gas_price);
+    let result = sqlx::query(
+        r#"
+        UPDATE fee_history
+        SET gas_used = ?3, effective_gas_price = ?4, fee_paid_wei = ?5, confirmed_at = ?6
+        WHERE network = ?1 AND tx_hash = ?2 AND gas_used IS NULL
+        "#,
+    )
+    .bind(network)
+    .bind(tx_hash)
+    .bind(gas_used as i64)
+    .bind(effective_gas_price as i64)
+    .bind(fee_paid.to_string())
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to complete fee history: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+const FEE_COLUMNS: &str = "network, tx_hash, gas_limit, gas_used, gas_price, max_fee_per_gas, \
+     max_priority_fee_per_gas, effective_gas_price, fee_paid_wei, speed, created_at, confirmed_at";
+
+pub async fn get(pool: &SqlitePool, network: &str, tx_hash: &str) -> Result<Option<FeeRecord>> {
+    let sql = format!("SELECT {} FROM fee_history WHERE network = ?1 AND tx_hash = ?2", FEE_COLUMNS);
+    Ok(sqlx::query_as::<_, FeeRecord>(&sql).bind(network).bind(tx_hash).fetch_optional(pool).await?)
+}
+
+/// Oldest rows created since `since` still waiting for a receipt. Older
+/// rows (dropped or replaced sends) are left unconfirmed.
+pub async fn unconfirmed(pool: &SqlitePool, since: i64, limit: i64) -> Result<Vec<FeeRecord>> {
+    let sql = format!(
+        "SELECT {} FROM fee_history WHERE gas_used IS NULL AND created_at >= ?1 ORDER BY created_at, id LIMIT ?2",
+        FEE_COLUMNS
+    );
+    Ok(sqlx::query_as::<_, FeeRecord>(&sql).bind(since).bind(limit).fetch_all(pool).await?)
+}
+
+/// [`unconfirmed`] restricted to `network`.
+pub async fn unconfirmed_on(pool: &SqlitePool, network: &str, since: i64, limit: i64) -> Result<Vec<FeeRecord>> {
+    let sql = format!(
+        "SELECT {} FROM fee_history WHERE gas_used IS NULL AND network = ?1 AND created_at >= ?2 \
+         ORDER BY created_at, id LIMIT ?3",
+        FEE_COLUMNS
+    );
+    Ok(sqlx::query_as::<_, FeeRecord>(&sql).bind(network).bind(since).bind(limit).fetch_all(pool).await?)
+}
+
+/// [`unconfirmed`] without the rows of `skip`.
+pub async fn unconfirmed_except(pool: &SqlitePool, skip: &[String], since: i64, limit: i64) -> Result<Vec<FeeRecord>> {
+    if skip.is_empty() {
+        return unconfirmed(pool, since, limit).await;
+    }
+    let placeholders = vec!["?"; skip.len()].join(", ");
+    let sql = format!(
+        "SELECT {} FROM fee_history WHERE gas_used IS NULL AND created_at >= ? AND network NOT IN ({}) \
+         ORDER BY created_at, id LIMIT ?",
+        FEE_COLUMNS, placeholders
+    );
+    let mut query = sqlx::query_as::<_, FeeRecord>(&sql).bind(since);
+    for network in skip {
+        query = query.bind(network);
+    }
+    Ok(query.bind(limit).fetch_all(pool).await?)
+}
+
+pub async fn insert_gas_sample(pool: &SqlitePool, network: &str, gas_price: u64, sampled_at: i64) -> Result<()> {
+    sqlx::query("INSERT INTO gas_price_samples (network, gas_price, sampled_at) VALUES (?1, ?2, ?3)")
+        .bind(network)
+        .bind(gas_price as i64)
+        .bind(sampled_at)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to record gas price sample: {}", e))?;
+    Ok(())
+}
+
+/// `(sampled_at, gas_price)` since `since`, oldest first
+pub async fn gas_samples(pool: &SqlitePool, network: &str, since: i64) -> Result<Vec<(i64, u64)>> {
+    let rows = sqlx::query(
+        "SELECT sampled_at, gas_price FROM gas_price_samples WHERE network = ?1 AND sampled_at >= ?2 \
+         ORDER BY sampled_at, id",
+    )
+    .bind(network)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|r| (r.get("sampled_at"), r.get::<i64, _>("gas_price") as u64)).collect())
+}
+
+/// Per-bucket summaries (ascending) and the window total.
+pub async fn summarize(
+    conn: &mut SqliteConnection,
+    network: &str,
+    since: i64,
+    grouping: FeeGrouping,
+) -> Result<(Vec<FeeSummary>, FeeSummary)> {
+    let buckets = summarize_by(conn, network, since, grouping.bucket_sql()).await?;
+    let total = summarize_by(conn, network, since, "0").await?.pop().map(|(_, total)| total).unwrap_or_else(|| {
+        FeeSummary { total_fee_wei: "0".to_string(), overpayment_wei: "0".to_string(), ..Default::default() }
+    });
+    let buckets = buckets
+        .into_iter()
+        .map(|(start, summary)| FeeSummary { bucket_start: Utc.timestamp_opt(start, 0).single(), ..summary })
+        .collect();
+    Ok((buckets, total))
+}
+
+/// `bucket` is one of the fixed expressions of [`FeeGrouping`] (or `0`).
+async fn summarize_by(
+    conn: &mut SqliteConnection,
+    network: &str,
+    since: i64,
+    bucket: &str,
+) -> Result<Vec<(i64, FeeSummary)>> {
+    // 计数 / 平均值在 SQL 中完成
+    let counts = sqlx::query(&format!(
+        r#"
+        SELECT {bucket} AS bucket,
+               COUNT(*) AS transactions,
+               COUNT(gas_used) AS confirmed,
+               COALESCE(SUM(gas_used), 0) AS total_gas_used,
+               CAST(AVG(effective_gas_price) AS INTEGER) AS avg_effective_gas_price
+        FROM fee_history
+        WHERE network = ?1 AND created_at >= ?2
+        GROUP BY bucket ORDER BY bucket
+        "#,
+    ))
+    .bind(network)
+    .bind(since)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let market = sqlx::query(&format!(
+        "SELECT {} AS bucket, CAST(AVG(gas_price) AS INTEGER) AS avg_gas_price FROM gas_price_samples \
+         WHERE network = ?1 AND sampled_at >= ?2 GROUP BY bucket",
+        bucket.replace("created_at", "sampled_at")
+    ))
+    .bind(network)
+    .bind(since)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    // 分位数与 u128 求和在 Rust 中完成
+    let receipts = sqlx::query(&format!(
+        r#"
+        SELECT {bucket} AS bucket, effective_gas_price, fee_paid_wei, max_fee_per_gas, gas_used
+        FROM fee_history
+        WHERE network = ?1 AND created_at >= ?2 AND gas_used IS NOT NULL
+        ORDER BY bucket, effective_gas_price
+        "#,
+    ))
+    .bind(network)
+    .bind(since)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut summaries = Vec::with_capacity(counts.len());
+    for row in counts {
+        let start: i64 = row.get("bucket");
+        let mut prices = Vec::new();
+        let mut total_fee: u128 = 0;
+        let mut overpayment: u128 = 0;
+        for receipt in receipts.iter().filter(|r| r.get::<i64, _>("bucket") == start) {
+            let effective = receipt.get::<Option<i64>, _>("effective_gas_price").unwrap_or_default() as u64;
+            let gas_used = receipt.get::<i64, _>("gas_used") as u64;
+            prices.push(effective);
+            total_fee += receipt
+                .get::<Option<String>, _>("fee_paid_wei")
+                .and_then(|fee| fee.parse::<u128>().ok())
+                .unwrap_or_default();
+            if let Some(max_fee) = receipt.get::<Option<i64>, _>("max_fee_per_gas") {
+                overpayment += overpayment_wei(max_fee as u64, effective, gas_used);
+            }
+        }
+
+        let confirmed = row.get::<i64, _>("confirmed") as u64;
+        summaries.push((
+            start,
+            FeeSummary {
+                bucket_start: None,
+                transactions: row.get::<i64, _>("transactions") as u64,
+                confirmed,
+                total_gas_used: row.get::<i64, _>("total_gas_used") as u64,
+                total_fee_wei: total_fee.to_string(),
+                avg_fee_wei: (confirmed > 0).then(|| (total_fee / u128::from(confirmed)).to_string()),
+                avg_effective_gas_price: row.get::<Option<i64>, _>("avg_effective_gas_price").map(|v| v as u64),
+                p50_effective_gas_price: percentile(&prices, 50),
+                p95_effective_gas_price: percentile(&prices, 95),
+                overpayment_wei: overpayment.to_string(),
+                market_avg_gas_price: market
+                    .iter()
+                    .find(|m| m.get::<i64, _>("bucket") == start)
+                    .and_then(|m| m.get::<Option<i64>, _>("avg_gas_price"))
+                    .map(|v| v as u64),
+            },
+        ));
+    }
+    Ok(summaries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overpayment_for_known_cap_and_effective_price() {
+        // 50 gwei cap, 32.5 gwei effective, plain transfer
+        assert_eq!(overpayment_wei(50_000_000_000, 32_500_000_000, 21_000), 367_500_000_000_000);
+        // effective above the cap cannot happen on chain; never negative
+        assert_eq!(overpayment_wei(10, 12, 21_000), 0);
+        // 30M gas at 500 gwei headroom overflows i64 but not u128
+        assert_eq!(overpayment_wei(600_000_000_000, 100_000_000_000, 30_000_000), 15_000_000_000_000_000_000);
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let prices: Vec<u64> = (1..=20).collect();
+        assert_eq!(percentile(&prices, 50), Some(10));
+        assert_eq!(percentile(&prices, 95), Some(19));
+        assert_eq!(percentile(&[7], 95), Some(7));
+        assert_eq!(percentile(&[], 50), None);
+    }
+}
@@ -0,0 +1,544 @@
+//! Backfilled on-chain history of imported wallets.
+//!
+//! `scan_cursors` holds one row per (wallet, network, address) that has been
+//! asked to backfill: the requested block range and the next block still to
+//! read. A batch of ledger entries and the cursor advance commit together, so
+//! an interrupted backfill resumes at the first block it had not stored.
+//!
+//! `ledger_entries` has one row per movement of value: the transaction's own
+//! native value and gas (`log_index = -1`) and each ERC-20 `Transfer` log
+//! naming the address. The unique key `(wallet_id, network, tx_hash,
+//! log_index)` is what makes re-running a range a no-op. Amounts and fees are
+//! decimal strings in the token's base units.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+/// Still has blocks to read
+pub const SCAN_RUNNING: &str = "running";
+/// Reached its target block
+pub const SCAN_COMPLETE: &str = "complete";
+
+/// `log_index` of the entry for a transaction's own value and gas
+pub const TX_ENTRY_INDEX: i64 = -1;
+
+/// Value received by the address
+pub const DIRECTION_IN: &str = "in";
+/// Value sent by the address
+pub const DIRECTION_OUT: &str = "out";
+/// Sent by the address to itself; only the fee changes its balance
+pub const DIRECTION_SELF: &str = "self";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct ScanCursor {
+    pub wallet_name: String,
+    pub network: String,
+    pub address: String,
+    pub from_block: i64,
+    /// Chain head when the backfill was requested
+    pub target_block: i64,
+    /// First block not yet stored
+    pub next_block: i64,
+    /// [`SCAN_RUNNING`] or [`SCAN_COMPLETE`]
+    pub status: String,
+    /// History strategy that last read the range: `rpc` or `explorer`
+    pub source: String,
+    /// Ledger entries stored so far
+    pub entries: i64,
+    /// Why the last batch failed; cleared by the next stored batch
+    pub last_error: Option<String>,
+    /// Unix seconds
+    pub requested_at: i64,
+    pub updated_at: i64,
+    pub completed_at: Option<i64>,
+}
+
+impl ScanCursor {
+    pub fn scope(&self) -> LedgerScope<'_> {
+        LedgerScope {
+            wallet_name: &self.wallet_name,
+            network: &self.network,
+            address: &self.address,
+            source: &self.source,
+        }
+    }
+}
+
+/// Whose ledger a batch of entries belongs to, and what read them
+#[derive(Debug, Clone, Copy)]
+pub struct LedgerScope<'a> {
+    pub wallet_name: &'a str,
+    pub network: &'a str,
+    pub address: &'a str,
+    /// `rpc` or `explorer`; `receipt` for a send's own receipt
+    pub source: &'a str,
+}
+
+/// A backfill to start or extend
+#[derive(Debug, Clone, Copy)]
+pub struct NewScanRequest<'a> {
+    pub wallet_name: &'a str,
+    pub network: &'a str,
+    pub address: &'a str,
+    pub from_block: i64,
+    pub target_block: i64,
+    pub source: &'a str,
+}
+
+/// One movement of value to store, already seen from the wallet's side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewLedgerEntry {
+    pub tx_hash: String,
+    /// [`TX_ENTRY_INDEX`] for the transaction itself
+    pub log_index: i64,
+    pub block_number: i64,
+    /// [`DIRECTION_IN`], [`DIRECTION_OUT`] or [`DIRECTION_SELF`]
+    pub direction: &'static str,
+    /// [`NATIVE_TOKEN`](super::NATIVE_TOKEN) or the lowercase contract address
+    pub token: String,
+    /// The other side; `None` for a contract creation
+    pub counterparty: Option<String>,
+    pub amount: U256,
+    /// Gas paid by the wallet; zero unless it sent the transaction
+    pub fee: U256,
+    pub success: bool,
+    /// Unix seconds of the block
+    pub occurred_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct LedgerEntryRecord {
+    pub id: i64,
+    pub wallet_id: String,
+    pub network: String,
+    pub address: String,
+    pub tx_hash: String,
+    pub log_index: i64,
+    pub block_number: i64,
+    pub direction: String,
+    pub token: String,
+    pub counterparty: Option<String>,
+    pub amount: String,
+    pub fee: String,
+    pub success: bool,
+    pub occurred_at: i64,
+    pub source: String,
+    pub created_at: i64,
+}
+
+/// Totals of one token over the stored entries, in base units.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerBalance {
+    pub token: String,
+    pub received: String,
+    pub sent: String,
+    pub fees: String,
+    /// `received - sent - fees`; negative when the range started after the
+    /// address was already funded
+    pub net: String,
+    pub entries: u64,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS scan_cursors (
+            wallet_name TEXT NOT NULL,
+            network TEXT NOT NULL,
+            address TEXT NOT NULL,
+            from_block INTEGER NOT NULL,
+            target_block INTEGER NOT NULL,
+            next_block INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            source TEXT NOT NULL,
+            entries INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT,
+            requested_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            completed_at INTEGER,
+            PRIMARY KEY (wallet_name, network, address)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS ledger_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            wallet_id TEXT NOT NULL,
+            network TEXT NOT NULL,
+            address TEXT NOT NULL,
+            tx_hash TEXT NOT NULL,
+            log_index INTEGER NOT NULL,
+            block_number INTEGER NOT NULL,
+            direction TEXT NOT NULL,
+            token TEXT NOT NULL,
+            counterparty TEXT,
+            amount TEXT NOT NULL,
+            fee TEXT NOT NULL,
+            success INTEGER NOT NULL,
+            occurred_at INTEGER NOT NULL,
+            source TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            UNIQUE (wallet_id, network, tx_hash, log_index)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_ledger_entries_wallet ON ledger_entries (wallet_id, network, block_number)",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_ledger_entries_tx ON ledger_entries (network, tx_hash)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+const CURSOR_COLUMNS: &str = "wallet_name, network, address, from_block, target_block, next_block, status, source, \
+     entries, last_error, requested_at, updated_at, completed_at";
+
+pub async fn cursor(
+    conn: &mut SqliteConnection,
+    wallet_name: &str,
+    network: &str,
+    address: &str,
+) -> Result<Option<ScanCursor>> {
+    Ok(sqlx::query_as::<_, ScanCursor>(&format!(
+        "SELECT {} FROM scan_cursors WHERE wallet_name = ?1 AND network = ?2 AND address = ?3",
+        CURSOR_COLUMNS
+    ))
+    .bind(wallet_name)
+    .bind(network)
+    .bind(address)
+    .fetch_optional(&mut *conn)
+    .await?)
+}
+
+/// Starts or extends a backfill of `from_block..=target_block`.
+///
+/// Asking again for a range already covered only moves the target forward
+/// to the new head; asking for an earlier `from_block` rewinds the cursor,
+/// and the blocks read before are skipped by the unique key when stored.
+pub async fn request(conn: &mut SqliteConnection, req: &NewScanRequest<'_>, now: i64) -> Result<ScanCursor> {
+    let NewScanRequest { wallet_name, network, address, from_block, target_block, source } = *req;
+    let existing = cursor(conn, wallet_name, network, address).await?;
+    let (from_block, next_block, entries) = match &existing {
+        Some(c) if c.from_block <= from_block => (c.from_block, c.next_block, c.entries),
+        Some(c) => (from_block, from_block, c.entries),
+        None => (from_block, from_block, 0),
+    };
+    let target_block = existing.as_ref().map_or(target_block, |c| c.target_block.max(target_block));
+    let (status, completed_at) = if next_block > target_block {
+        (SCAN_COMPLETE, Some(existing.as_ref().and_then(|c| c.completed_at).unwrap_or(now)))
+    } else {
+        (SCAN_RUNNING, None)
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO scan_cursors (wallet_name, network, address, from_block, target_block, next_block, status,
+                                  source, entries, last_error, requested_at, updated_at, completed_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, NULL, ?10, ?10, ?11)
+        ON CONFLICT(wallet_name, network, address) DO UPDATE SET
+            from_block = excluded.from_block,
+            target_block = excluded.target_block,
+            next_block = excluded.next_block,
+            status = excluded.status,
+            source = excluded.source,
+            last_error = NULL,
+            requested_at = excluded.requested_at,
+            updated_at = excluded.updated_at,
+            completed_at = excluded.completed_at
+        "#,
+    )
+    .bind(wallet_name)
+    .bind(network)
+    .bind(address)
+    .bind(from_block)
+    .bind(target_block)
+    .bind(next_block)
+    .bind(status)
+    .bind(source)
+    .bind(entries)
+    .bind(now)
+    .bind(completed_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store scan cursor: {}", e))?;
+    cursor(conn, wallet_name, network, address)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Scan cursor vanished for {} on {}", wallet_name, network))
+}
+
+/// Cursors with blocks left, oldest request first
+pub async fn running(pool: &SqlitePool) -> Result<Vec<ScanCursor>> {
+    Ok(sqlx::query_as::<_, ScanCursor>(&format!(
+        "SELECT {} FROM scan_cursors WHERE status = ?1 ORDER BY requested_at, wallet_name, network",
+        CURSOR_COLUMNS
+    ))
+    .bind(SCAN_RUNNING)
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn for_wallet(pool: &SqlitePool, wallet_name: &str) -> Result<Vec<ScanCursor>> {
+    Ok(sqlx::query_as::<_, ScanCursor>(&format!(
+        "SELECT {} FROM scan_cursors WHERE wallet_name = ?1 ORDER BY network, address",
+        CURSOR_COLUMNS
+    ))
+    .bind(wallet_name)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Inserts `entries`, returning the ones that were not stored before.
+pub async fn insert_entries(
+    conn: &mut SqliteConnection,
+    scope: &LedgerScope<'_>,
+    entries: &[NewLedgerEntry],
+    now: i64,
+) -> Result<Vec<NewLedgerEntry>> {
+    let mut inserted = Vec::new();
+    for entry in entries {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO ledger_entries (wallet_id, network, address, tx_hash, log_index, block_number, direction,
+                                        token, counterparty, amount, fee, success, occurred_at, source, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+            ON CONFLICT(wallet_id, network, tx_hash, log_index) DO NOTHING
+            "#,
+        )
+        .bind(scope.wallet_name)
+        .bind(scope.network)
+        .bind(scope.address)
+        .bind(&entry.tx_hash)
+        .bind(entry.log_index)
+        .bind(entry.block_number)
+        .bind(entry.direction)
+        .bind(&entry.token)
+        .bind(&entry.counterparty)
+        .bind(entry.amount.to_string())
+        .bind(entry.fee.to_string())
+        .bind(entry.success)
+        .bind(entry.occurred_at)
+        .bind(scope.source)
+        .bind(now)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to store ledger entry: {}", e))?;
+        if result.rows_affected() == 1 {
+            inserted.push(entry.clone());
+        }
+    }
+    Ok(inserted)
+}
+
+/// Marks `..=through_block` stored and clears the last error; completes the
+/// cursor once it passes its target.
+pub async fn advance(
+    conn: &mut SqliteConnection,
+    cursor: &ScanCursor,
+    through_block: i64,
+    inserted: i64,
+    now: i64,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE scan_cursors SET
+            next_block = ?1,
+            entries = entries + ?2,
+            last_error = NULL,
+            status = CASE WHEN ?1 > target_block THEN ?3 ELSE ?4 END,
+            completed_at = CASE WHEN ?1 > target_block THEN ?5 ELSE NULL END,
+            updated_at = ?5
+        WHERE wallet_name = ?6 AND network = ?7 AND address = ?8
+        "#,
+    )
+    .bind(through_block + 1)
+    .bind(inserted)
+    .bind(SCAN_COMPLETE)
+    .bind(SCAN_RUNNING)
+    .bind(now)
+    .bind(&cursor.wallet_name)
+    .bind(&cursor.network)
+    .bind(&cursor.address)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to advance scan cursor: {}", e))?;
+    Ok(())
+}
+
+pub async fn record_error(pool: &SqlitePool, cursor: &ScanCursor, error: &str, now: i64) -> Result<()> {
+    sqlx::query(
+        "UPDATE scan_cursors SET last_error = ?1, updated_at = ?2 \
+         WHERE wallet_name = ?3 AND network = ?4 AND address = ?5",
+    )
+    .bind(error)
+    .bind(now)
+    .bind(&cursor.wallet_name)
+    .bind(&cursor.network)
+    .bind(&cursor.address)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+const ENTRY_COLUMNS: &str = "id, wallet_id, network, address, tx_hash, log_index, block_number, direction, token, \
+     counterparty, amount, fee, success, occurred_at, source, created_at";
+
+pub async fn entries(pool: &SqlitePool, wallet_name: &str, network: &str) -> Result<Vec<LedgerEntryRecord>> {
+    Ok(sqlx::query_as::<_, LedgerEntryRecord>(&format!(
+        "SELECT {} FROM ledger_entries WHERE wallet_id = ?1 AND network = ?2 ORDER BY block_number, log_index, id",
+        ENTRY_COLUMNS
+    ))
+    .bind(wallet_name)
+    .bind(network)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Entries of one transaction across wallets (both sides of a transfer
+/// between two of them); `tx_hash` is lowercase hex, as stored.
+pub async fn entries_for_transaction(
+    pool: &SqlitePool,
+    network: &str,
+    tx_hash: &str,
+) -> Result<Vec<LedgerEntryRecord>> {
+    Ok(sqlx::query_as::<_, LedgerEntryRecord>(&format!(
+        "SELECT {} FROM ledger_entries WHERE network = ?1 AND tx_hash = ?2 ORDER BY wallet_id, log_index",
+        ENTRY_COLUMNS
+    ))
+    .bind(network)
+    .bind(tx_hash)
+    .fetch_all(pool)
+    .await?)
+}
+
+fn signed(value: U256, negative: bool) -> String {
+    if negative && !value.is_zero() {
+        format!("-{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Per-token totals of `entries`, sorted by token. Failed transactions count
+/// their fee only.
+pub fn balances(entries: &[LedgerEntryRecord]) -> Result<Vec<LedgerBalance>> {
+    #[derive(Default)]
+    struct Totals {
+        received: U256,
+        sent: U256,
+        fees: U256,
+        entries: u64,
+    }
+    let mut totals: BTreeMap<&str, Totals> = BTreeMap::new();
+    for entry in entries {
+        let amount = U256::from_dec_str(&entry.amount)
+            .map_err(|_| anyhow::anyhow!("Ledger entry {} has amount {:?}", entry.id, entry.amount))?;
+        let fee = U256::from_dec_str(&entry.fee)
+            .map_err(|_| anyhow::anyhow!("Ledger entry {} has fee {:?}", entry.id, entry.fee))?;
+        let token = totals.entry(entry.token.as_str()).or_default();
+        token.entries += 1;
+        token.fees = token.fees.saturating_add(fee);
+        if !entry.success {
+            continue;
+        }
+        match entry.direction.as_str() {
+            DIRECTION_IN => token.received = token.received.saturating_add(amount),
+            DIRECTION_OUT => token.sent = token.sent.saturating_add(amount),
+            _ => {}
+        }
+    }
+    Ok(totals
+        .into_iter()
+        .map(|(token, t)| {
+            let spent = t.sent.saturating_add(t.fees);
+            let net = if t.received >= spent {
+                signed(t.received - spent, false)
+            } else {
+                signed(spent - t.received, true)
+            };
+            LedgerBalance {
+                token: token.to_string(),
+                received: t.received.to_string(),
+                sent: t.sent.to_string(),
+                fees: t.fees.to_string(),
+                net,
+                entries: t.entries,
+            }
+        })
+        .collect())
+}
+
+/// Drops the cursors of a deleted wallet. Ledger entries stay, like its
+/// transactions do.
+pub async fn delete_for_wallet(conn: &mut SqliteConnection, wallet_name: &str) -> Result<()> {
+    sqlx::query("DELETE FROM scan_cursors WHERE wallet_name = ?1").bind(wallet_name).execute(&mut *conn).await?;
+    Ok(())
+}
+
+pub async fn rename(conn: &mut SqliteConnection, wallet_name: &str, new_name: &str) -> Result<()> {
+    sqlx::query("UPDATE scan_cursors SET wallet_name = ?1 WHERE wallet_name = ?2")
+        .bind(new_name)
+        .bind(wallet_name)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("UPDATE ledger_entries SET wallet_id = ?1 WHERE wallet_id = ?2")
+        .bind(new_name)
+        .bind(wallet_name)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(token: &str, direction: &str, amount: u64, fee: u64, success: bool) -> LedgerEntryRecord {
+        LedgerEntryRecord {
+            id: 1,
+            wallet_id: "w".into(),
+            network: "eth".into(),
+            address: "0xaa".into(),
+            tx_hash: "0x01".into(),
+            log_index: TX_ENTRY_INDEX,
+            block_number: 1,
+            direction: direction.into(),
+            token: token.into(),
+            counterparty: None,
+            amount: amount.to_string(),
+            fee: fee.to_string(),
+            success,
+            occurred_at: 0,
+            source: "rpc".into(),
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_balances_count_fees_of_failed_transactions_only() {
+        let entries = [
+            entry("native", DIRECTION_IN, 100, 0, true),
+            entry("native", DIRECTION_OUT, 30, 2, true),
+            entry("native", DIRECTION_OUT, 50, 3, false),
+            entry("native", DIRECTION_SELF, 10, 1, true),
+            entry("0xcc", DIRECTION_OUT, 7, 0, true),
+        ];
+        let balances = balances(&entries).unwrap();
+        assert_eq!(balances[0].token, "0xcc");
+        assert_eq!(balances[0].net, "-7");
+        let native = &balances[1];
+        assert_eq!((native.received.as_str(), native.sent.as_str(), native.fees.as_str()), ("100", "30", "6"));
+        assert_eq!(native.net, "64");
+        assert_eq!(native.entries, 4);
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
-use sqlx::types::chrono::Utc;
-use sqlx::{Row, SqlitePool};
+use sqlx::{types::chrono::NaiveDateTime, Row, SqliteConnection, SqlitePool};
+
+use super::wallet_creation::CREATION_COMPLETE;
 
 #[derive(Debug, Clone)]
 pub struct KeyLabelRecord {
@@ -19,6 +20,32 @@ pub struct KeyVersionRecord {
     pub created_at: i64,
 }
 
+/// A stored wallet envelope as read inside a rewrap transaction
+#[derive(Debug, Clone)]
+pub struct WalletEnvelope {
+    pub name: String,
+    pub encrypted_data: Vec<u8>,
+    pub quantum_safe: bool,
+    /// Approvals on the wallet still waiting for a decision
+    pub pending_approvals: i64,
+    /// Transactions of the wallet not yet confirmed or failed
+    pub pending_transactions: i64,
+}
+
+/// Replacement blob for a [`WalletEnvelope`]
+#[derive(Debug, Clone)]
+pub struct RewrappedEnvelope {
+    pub encrypted_data: Vec<u8>,
+    pub quantum_safe: bool,
+}
+
+/// What a rewrap transaction did before it ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewrapSummary {
+    pub rows_updated: u64,
+    pub committed: bool,
+}
+
 pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
     sqlx::query(
         r#"
@@ -77,8 +104,8 @@ pub async fn insert_version(
     label: &str,
     version: i64,
     key_id: &str,
+    now: i64,
 ) -> Result<()> {
-    let now = Utc::now().timestamp();
     sqlx::query(
         r#"
         INSERT INTO key_versions (label, version, key_id, retired, usage_count, created_at)
@@ -94,11 +121,14 @@ pub async fn insert_version(
     Ok(())
 }
 
-pub async fn mark_retired(pool: &SqlitePool, label: &str, version: i64) -> Result<()> {
+pub async fn mark_retired<'e, E>(executor: E, label: &str, version: i64) -> Result<()>
+where
+    E: sqlx::SqliteExecutor<'e>,
+{
     sqlx::query("UPDATE key_versions SET retired=1 WHERE label=?1 AND version=?2")
         .bind(label)
         .bind(version)
-        .execute(pool)
+        .execute(executor)
         .await?;
     Ok(())
 }
@@ -114,6 +144,19 @@ pub async fn inc_usage(pool: &SqlitePool, label: &str, version: i64) -> Result<(
     Ok(())
 }
 
+/// Adds `count` signatures at once; used by the batched usage flusher.
+pub async fn inc_usage_by(pool: &SqlitePool, label: &str, version: i64, count: i64) -> Result<()> {
+    sqlx::query(
+        "UPDATE key_versions SET usage_count = usage_count + ?3 WHERE label=?1 AND version=?2",
+    )
+    .bind(label)
+    .bind(version)
+    .bind(count)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 pub async fn get_label(pool: &SqlitePool, label: &str) -> Result<Option<KeyLabelRecord>> {
     let row =
         sqlx::query("SELECT label, current_version, current_id FROM key_labels WHERE label=?1")
@@ -146,3 +189,50 @@ pub async fn get_version(
         created_at: r.get("created_at"),
     }))
 }
+
+/// Every completed wallet with its pending work, ordered by name.
+pub async fn envelopes(conn: &mut SqliteConnection) -> Result<Vec<WalletEnvelope>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT w.name, w.encrypted_data, w.quantum_safe,
+            (SELECT COUNT(*) FROM pending_approvals a WHERE a.wallet_name = w.name AND a.status = 'pending')
+                AS pending_approvals,
+            (SELECT COUNT(*) FROM transactions t WHERE t.wallet_id = w.id AND t.status = 'pending')
+                AS pending_transactions
+        FROM wallets w
+        WHERE w.creation_state = ?1
+        ORDER BY w.name
+        "#,
+    )
+    .bind(CREATION_COMPLETE)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| WalletEnvelope {
+            name: r.get("name"),
+            encrypted_data: r.get("encrypted_data"),
+            quantum_safe: r.get("quantum_safe"),
+            pending_approvals: r.get("pending_approvals"),
+            pending_transactions: r.get("pending_transactions"),
+        })
+        .collect())
+}
+
+pub async fn replace_envelope(
+    conn: &mut SqliteConnection,
+    name: &str,
+    envelope: &RewrappedEnvelope,
+    now: NaiveDateTime,
+) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE wallets SET encrypted_data = ?1, quantum_safe = ?2, updated_at = ?3 WHERE name = ?4",
+    )
+    .bind(&envelope.encrypted_data)
+    .bind(envelope.quantum_safe)
+    .bind(now)
+    .bind(name)
+    .execute(&mut *conn)
+    .await?;
+    Ok(result.rows_affected())
+}
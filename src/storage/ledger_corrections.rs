@@ -0,0 +1,108 @@
+//! Corrections to transaction outcomes that were already reported.
+//!
+//! A transaction marked `expired` was reported to consumers as never having
+//! happened; when it is mined after all, the balance effect it was assumed
+//! not to have must be booked. Each such late transition writes one row here,
+//! in the same database transaction as the status change. Rows are append-only
+//! and carry the transaction's `wallet_id`, a `wallets.id`.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+use super::TransactionRecord;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct LedgerCorrection {
+    pub id: i64,
+    pub transaction_id: String,
+    pub wallet_id: String,
+    pub network: String,
+    pub tx_hash: String,
+    /// Status the transaction was reported with (`expired`)
+    pub from_status: String,
+    pub to_status: String,
+    pub amount: String,
+    pub fee: String,
+    pub reason: String,
+    /// Unix seconds
+    pub created_at: i64,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS ledger_corrections (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            transaction_id TEXT NOT NULL,
+            wallet_id TEXT NOT NULL,
+            network TEXT NOT NULL,
+            tx_hash TEXT NOT NULL,
+            from_status TEXT NOT NULL,
+            to_status TEXT NOT NULL,
+            amount TEXT NOT NULL,
+            fee TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_ledger_corrections_wallet ON ledger_corrections (wallet_id, created_at)",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Books `tx` (already carrying its new status) as corrected from `from_status`.
+pub async fn insert(
+    conn: &mut SqliteConnection,
+    tx: &TransactionRecord,
+    from_status: &str,
+    reason: &str,
+    now: i64,
+) -> Result<i64> {
+    let id = sqlx::query_scalar(
+        r#"
+        INSERT INTO ledger_corrections
+            (transaction_id, wallet_id, network, tx_hash, from_status, to_status, amount, fee, reason, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        RETURNING id
+        "#,
+    )
+    .bind(&tx.id)
+    .bind(&tx.wallet_id)
+    .bind(&tx.network)
+    .bind(&tx.tx_hash)
+    .bind(from_status)
+    .bind(&tx.status)
+    .bind(&tx.amount)
+    .bind(&tx.fee)
+    .bind(reason)
+    .bind(now)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record ledger correction: {}", e))?;
+    Ok(id)
+}
+
+/// Oldest first; all wallets when `wallet_id` is `None`.
+pub async fn list(pool: &SqlitePool, wallet_id: Option<&str>, limit: i64) -> Result<Vec<LedgerCorrection>> {
+    sqlx::query_as::<_, LedgerCorrection>(
+        r#"
+        SELECT id, transaction_id, wallet_id, network, tx_hash, from_status, to_status, amount, fee, reason, created_at
+        FROM ledger_corrections
+        WHERE ?1 IS NULL OR wallet_id = ?1
+        ORDER BY id
+        LIMIT ?2
+        "#,
+    )
+    .bind(wallet_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to list ledger corrections: {}", e))
+}
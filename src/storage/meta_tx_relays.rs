@@ -0,0 +1,225 @@
+//! Relayed meta-transactions and per-sender relay quotas.
+//!
+//! A relay row is reserved before submission so the same forward request
+//! (forwarder, sender, nonce) cannot be relayed twice; a failed submission
+//! releases the reservation by moving the row to `failed`. Addresses are
+//! stored as lowercase `0x` hex.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow};
+
+/// Reserved, `execute` not broadcast yet.
+pub const RELAY_PENDING: &str = "pending";
+/// `execute` broadcast; `tx_hash` is set.
+pub const RELAY_SUBMITTED: &str = "submitted";
+/// Submission failed; the request may be relayed again.
+pub const RELAY_FAILED: &str = "failed";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct MetaTxRelayRecord {
+    pub id: String,
+    /// Signer of the forward request
+    pub from_address: String,
+    /// Target contract of the forward request
+    pub to_address: String,
+    pub forwarder: String,
+    /// Forwarder nonce of the request (decimal)
+    pub request_nonce: String,
+    pub network: String,
+    pub relayer_wallet: String,
+    pub status: String,
+    /// Hash of the relayer's `execute` transaction
+    pub tx_hash: Option<String>,
+    pub error: Option<String>,
+    /// Unix seconds
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Fields of a new `meta_tx_relays` row.
+#[derive(Debug, Clone)]
+pub struct NewMetaTxRelay<'a> {
+    pub id: &'a str,
+    pub from_address: &'a str,
+    pub to_address: &'a str,
+    pub forwarder: &'a str,
+    pub request_nonce: &'a str,
+    pub network: &'a str,
+    pub relayer_wallet: &'a str,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS relay_quotas (
+            from_address TEXT PRIMARY KEY,
+            window_start INTEGER NOT NULL,
+            used INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS meta_tx_relays (
+            id TEXT PRIMARY KEY,
+            from_address TEXT NOT NULL,
+            to_address TEXT NOT NULL,
+            forwarder TEXT NOT NULL,
+            request_nonce TEXT NOT NULL,
+            network TEXT NOT NULL,
+            relayer_wallet TEXT NOT NULL,
+            status TEXT NOT NULL,
+            tx_hash TEXT,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // failed attempts do not block a retry of the same request
+    sqlx::query(
+        r#"
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_meta_tx_relays_request
+        ON meta_tx_relays (forwarder, from_address, request_nonce)
+        WHERE status != 'failed'
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_meta_tx_relays_from ON meta_tx_relays (from_address, created_at)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Takes one unit of `from_address`'s quota. The window restarts once
+/// `window_secs` have passed since it opened. Returns `false`, without
+/// changing anything, when the current window is used up.
+pub async fn consume_quota(
+    pool: &SqlitePool,
+    from_address: &str,
+    limit: u32,
+    window_secs: u64,
+    now: i64,
+) -> Result<bool> {
+    if limit == 0 {
+        return Ok(false);
+    }
+    let window_floor = now - window_secs as i64;
+    let used: Option<i64> = sqlx::query_scalar(
+        r#"
+        INSERT INTO relay_quotas (from_address, window_start, used)
+        VALUES (?1, ?2, 1)
+        ON CONFLICT(from_address) DO UPDATE SET
+            used = CASE WHEN window_start <= ?3 THEN 1 ELSE used + 1 END,
+            window_start = CASE WHEN window_start <= ?3 THEN ?2 ELSE window_start END
+        WHERE window_start <= ?3 OR used < ?4
+        RETURNING used
+        "#,
+    )
+    .bind(from_address)
+    .bind(now)
+    .bind(window_floor)
+    .bind(limit as i64)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to update relay quota: {}", e))?;
+
+    Ok(used.is_some())
+}
+
+/// Gives back one unit taken by [`consume_quota`].
+pub async fn release_quota(pool: &SqlitePool, from_address: &str) -> Result<()> {
+    sqlx::query("UPDATE relay_quotas SET used = used - 1 WHERE from_address = ?1 AND used > 0")
+        .bind(from_address)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to release relay quota: {}", e))?;
+    Ok(())
+}
+
+/// Inserts a `pending` row. Returns `false` if the same request is already
+/// pending or submitted.
+pub async fn reserve(pool: &SqlitePool, relay: &NewMetaTxRelay<'_>, now: i64) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO meta_tx_relays (
+            id, from_address, to_address, forwarder, request_nonce, network,
+            relayer_wallet, status, created_at, updated_at
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(relay.id)
+    .bind(relay.from_address)
+    .bind(relay.to_address)
+    .bind(relay.forwarder)
+    .bind(relay.request_nonce)
+    .bind(relay.network)
+    .bind(relay.relayer_wallet)
+    .bind(RELAY_PENDING)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to reserve relay: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn mark_submitted(pool: &SqlitePool, id: &str, tx_hash: &str, now: i64) -> Result<()> {
+    sqlx::query("UPDATE meta_tx_relays SET status = ?1, tx_hash = ?2, updated_at = ?3 WHERE id = ?4")
+        .bind(RELAY_SUBMITTED)
+        .bind(tx_hash)
+        .bind(now)
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to record relay submission: {}", e))?;
+    Ok(())
+}
+
+pub async fn mark_failed(pool: &SqlitePool, id: &str, error: &str, now: i64) -> Result<()> {
+    sqlx::query("UPDATE meta_tx_relays SET status = ?1, error = ?2, updated_at = ?3 WHERE id = ?4")
+        .bind(RELAY_FAILED)
+        .bind(error)
+        .bind(now)
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to record relay failure: {}", e))?;
+    Ok(())
+}
+
+/// Relays of one sender, most recent first.
+pub async fn list_for_sender(
+    pool: &SqlitePool,
+    from_address: &str,
+    limit: i64,
+) -> Result<Vec<MetaTxRelayRecord>> {
+    sqlx::query_as::<_, MetaTxRelayRecord>(
+        r#"
+        SELECT id, from_address, to_address, forwarder, request_nonce, network, relayer_wallet,
+               status, tx_hash, error, created_at, updated_at
+        FROM meta_tx_relays
+        WHERE from_address = ?1
+        ORDER BY created_at DESC, rowid DESC
+        LIMIT ?2
+        "#,
+    )
+    .bind(from_address)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load relays: {}", e))
+}
@@ -6,16 +6,223 @@ use sha2::{Digest, Sha256};
 use sqlx::types::chrono::Utc;
 use sqlx::{sqlite::SqlitePool, types::chrono::NaiveDateTime, FromRow, Row};
 use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, info, warn}; // for base64 engine decode
 
 use crate::blockchain::bridge::{BridgeTransaction, BridgeTransactionStatus};
+use crate::blockchain::erc20::TokenBehavior;
+use crate::core::address_book::AddressBookEntry;
+use crate::core::clock::{system_clock, Clock};
+use crate::core::ids::{random_ids, IdGenerator};
+use crate::security::approval_key::ApprovalKey;
+use crate::security::signing_secret::SealedSigningSecret;
+use crate::util::retry::{retry, Jitter, RetryPolicy, StopReason};
+mod address_book;
+mod approvals;
+mod attestations;
+mod audit_chain;
+mod backup_history;
+mod bridge_query;
+mod balance_snapshots;
+mod balance_subscriptions;
+mod cache_epochs;
+mod deadman_switches;
+mod delegations;
+mod distributed_locks;
+mod erasure;
+mod erc20_tokens;
+mod events_journal;
+mod feature_flags;
+mod fee_history;
+mod history_backfill;
 mod key_rotation;
-pub use key_rotation::{KeyLabelRecord, KeyVersionRecord};
+mod ledger_corrections;
+mod meta_tx_relays;
+mod multisig_policies;
+mod nonce_lanes;
+mod nonce_reservations;
+mod operation_bundles;
+#[cfg(feature = "postgres")]
+mod postgres;
+mod process_incidents;
+mod query_guard;
+mod reconciliation_runs;
+mod request_nonces;
+mod reserve_reports;
+mod sandbox;
+mod schema_migrations;
+mod signing_intents;
+mod spending_limits;
+mod timelocks;
+mod transaction_decisions;
+mod tx_query;
+mod user_operations;
+mod wal;
+mod wallet_cache;
+mod wallet_creation;
+mod wallet_groups;
+mod wallet_networks;
+mod wallet_notes;
+mod wallet_page;
+mod wallet_profiles;
+mod wallet_tokens;
+pub use attestations::{AttestationRecord, NewAttestation};
+pub use audit_chain::AuditChainReport;
+pub use backup_history::{BackupRecord, NewBackupRecord, BACKUP_FAILED, BACKUP_VERIFIED};
+pub use balance_snapshots::{BalancePoint, BalanceSnapshot, SnapshotResolution, NATIVE_TOKEN};
+pub use balance_subscriptions::{
+    BalanceObservation, BalanceSubscriptionRecord, NewBalanceSubscription, SubscriptionStep,
+};
+pub use bridge_query::BridgeFilter;
+pub use deadman_switches::{DeadmanPolicy, NewDeadmanPolicy, DEADMAN_ARMED, DEADMAN_TRIGGERED};
+pub use delegations::{DelegationBudget, DelegationRecord, NewDelegation, Reservation};
+pub use distributed_locks::LockRecord;
+pub use approvals::{
+    ApprovalDecision, ApprovalPayload, ApprovalRecord, APPROVAL_APPROVED, APPROVAL_EXPIRED, APPROVAL_FAILED,
+    APPROVAL_PENDING, APPROVAL_REJECTED,
+};
+pub use erasure::{ErasureCertificate, WalletErasure, ERASED_MARKER};
+pub use erc20_tokens::{
+    NewTokenDelivery, TokenBehaviorRecord, TokenDeliveryRecord, DELIVERY_DELIVERED, DELIVERY_PENDING, DELIVERY_SHORT,
+    DELIVERY_UNDELIVERED,
+};
+pub use events_journal::{JournalEvent, NewJournalEvent};
+pub use feature_flags::{FeatureFlagRecord, NewFeatureFlag};
+pub use fee_history::{overpayment_wei, FeeGrouping, FeeRecord, FeeSummary, NewFeeRecord};
+pub use history_backfill::{
+    LedgerBalance, LedgerEntryRecord, LedgerScope, NewLedgerEntry, NewScanRequest, ScanCursor, DIRECTION_IN,
+    DIRECTION_OUT, DIRECTION_SELF, SCAN_COMPLETE, SCAN_RUNNING, TX_ENTRY_INDEX,
+};
+/// Event type names written to the events journal
+pub mod journal_events {
+    pub use super::events_journal::{
+        APPROVAL_REQUESTED, APPROVAL_STATUS_CHANGED, AUDIT_REMACED, BALANCE_CHANGED, BRIDGE_STATUS_CHANGED,
+        BUNDLE_CREATED, BUNDLE_STATUS_CHANGED, BUNDLE_STEP_STATUS_CHANGED, DEADMAN_SWITCH_TRIGGERED,
+        DEADMAN_SWITCH_WARNING, FEATURE_FLAG_CHANGED, KEY_ROTATED, LEDGER_CORRECTED, LIMITS_CHANGED, REQUEST_FAILED,
+        SECURITY_EVENT, TRANSACTION_CREATED, TRANSACTION_DECIDED, TRANSACTION_EXPIRED, TRANSACTION_STATUS_CHANGED,
+        USER_ERASED, WALLET_CREATED, WALLET_DELETED, WALLET_RENAMED,
+    };
+}
+pub use key_rotation::{KeyLabelRecord, KeyVersionRecord, RewrapSummary, RewrappedEnvelope, WalletEnvelope};
+pub use ledger_corrections::LedgerCorrection;
+pub use meta_tx_relays::{
+    MetaTxRelayRecord, NewMetaTxRelay, RELAY_FAILED, RELAY_PENDING, RELAY_SUBMITTED,
+};
+pub use multisig_policies::MultisigPolicyRecord;
+pub use nonce_lanes::{LaneReservation, NonceLane, FUTURE_LANE};
+pub use nonce_reservations::NonceReservation;
+pub use operation_bundles::{
+    BundleRecord, BundleStepRecord, NewBundle, NewBundleStep, StepProgress, BUNDLE_COMPLETED, BUNDLE_FAILED,
+    BUNDLE_INTERRUPTED, BUNDLE_PARTIALLY_COMPLETED, BUNDLE_PENDING, BUNDLE_RUNNING, STEP_COMPLETED, STEP_FAILED,
+    STEP_PENDING, STEP_RUNNING, STEP_SKIPPED,
+};
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresWalletStorage;
+pub use process_incidents::{IncidentRecord, NewIncident};
+pub use query_guard::{GuardedConnection, PoolStats, QueryClass, QueryError, QueryTimeouts};
+pub use reconciliation_runs::{
+    Discrepancy, DiscrepancyKind, NewReconciliationRun, ReconciliationRun, ReconciliationTotals, StoredDiscrepancy,
+    RUN_COMPLETED, RUN_FAILED, RUN_QUEUED, RUN_RUNNING,
+};
+pub use reserve_reports::{NewReserveReport, ReserveReportRecord, ReserveReportSummary};
+pub use sandbox::{
+    probe as probe_sandbox, AddressRewrite, AddressRewrites, CloneSource, SourceWallet, CLONED_ATTESTATION_REASON,
+    SANDBOX_SETTING, SKIPPED_TABLES,
+};
+pub use schema_migrations::SCHEMA_VERSION;
+pub use signing_intents::{
+    NewSigningIntent, SigningIntentRecord, INTENT_BROADCAST, INTENT_RECORDED, INTENT_RELEASED,
+    INTENT_SIGNED, INTENT_SIGNING,
+};
+pub use spending_limits::{LimitExceeded, SpendingLimitRecord};
+pub use timelocks::{
+    NewTimelock, TimelockRecord, TIMELOCK_EXPIRED, TIMELOCK_INVALIDATED, TIMELOCK_LOCKED, TIMELOCK_RELEASED,
+};
+pub use tx_query::{TransactionCursor, TransactionFilter};
+pub use user_operations::{
+    NewUserOperation, UserOperationRecord, USER_OP_INCLUDED, USER_OP_PENDING, USER_OP_REVERTED,
+};
+pub use wal::{CheckpointOutcome, DatabaseFileStats, WalSession, WalSettings};
+pub use wallet_cache::{WalletCacheSettings, WalletCacheStats};
+pub use wallet_networks::{
+    DepositScanCursor, NetworkInit, WalletNetworkRecord, NETWORK_NEEDS_SYNC, NETWORK_READY,
+};
+pub use wallet_creation::{
+    CreationFault, CreationStep, PendingWalletDecision, PendingWalletReport, CREATION_COMPLETE, CREATION_PENDING,
+};
+pub use wallet_groups::{WalletGroupMember, WalletGroupRecord};
+pub use wallet_notes::{
+    is_valid_metadata_key, like_pattern, NotesUpdate, WalletNotes, MAX_DESCRIPTION_BYTES, MAX_METADATA_BYTES,
+    MAX_METADATA_KEYS, MAX_METADATA_KEY_LEN,
+};
+pub use wallet_page::{WalletCursor, WalletPage, MAX_WALLET_PAGE_SIZE};
+pub use wallet_profiles::ProfileRebuildReport;
+pub use wallet_tokens::{
+    hash_token, NewWalletToken, WalletCapability, WalletTokenRecord, AUTH_MODE_BEARER, AUTH_MODE_HMAC,
+};
+
+/// Pool that served a storage operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbPool {
+    Writer,
+    Reader,
+}
+
+impl DbPool {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DbPool::Writer => "writer",
+            DbPool::Reader => "reader",
+        }
+    }
+}
+
+/// Freshness a routed read needs when a read replica is configured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadConsistency {
+    /// Any replica state (listings, exports, statistics)
+    Eventual,
+    /// Must observe every commit up to this journal sequence, e.g. reading
+    /// back what the same request just wrote
+    AfterSeq(i64),
+    /// Always the writer
+    Primary,
+}
 
 #[derive(Debug)]
 pub struct WalletStorage {
+    /// Writer: every mutation and read-modify-write sequence
     pool: SqlitePool,
+    /// Optional read replica for listings, history, audit and statistics
+    read_pool: Option<SqlitePool>,
+    /// Per-pool operation counts (`database_pool_operations_total{pool}`)
+    metrics: Option<Arc<crate::monitoring::WalletMetrics>>,
     is_memory: bool,
+    /// Highest committed journal sequence; wakes long-polling consumers
+    journal_tip: Arc<tokio::sync::watch::Sender<i64>>,
+    /// Lock holder identity of this process, generated at startup
+    instance_id: Arc<str>,
+    /// When set, nonce reservations run under a per-(network, address) lease
+    nonce_lease: Option<std::time::Duration>,
+    /// Cancelled on server shutdown; aborts lease waits mid-backoff
+    shutdown: tokio_util::sync::CancellationToken,
+    /// Timestamps of written rows; pinned in tests
+    clock: Arc<dyn Clock>,
+    /// Ids of new wallets and bridge rows; sequential in tests
+    ids: Arc<dyn IdGenerator>,
+    /// Fault injected into wallet creation; tests only
+    creation_fault: Option<CreationFault>,
+    /// Wallet list / by-name metadata, invalidated through `cache_epochs`
+    wallet_cache: Arc<wallet_cache::WalletCache>,
+    /// Budgets of [`Self::timed`] queries
+    query_timeouts: QueryTimeouts,
+    /// Longest connection waits since the last [`Self::pool_stats`]
+    writer_wait: Arc<query_guard::AcquireWait>,
+    reader_wait: Arc<query_guard::AcquireWait>,
+    /// Per (wallet, network) send serialization for spending limits
+    send_locks: Arc<spending_limits::SendLocks>,
 }
 
 impl WalletStorage {
@@ -25,6 +232,16 @@ impl WalletStorage {
     }
 
     pub async fn new_with_url(database_url: &str) -> Result<Self> {
+        Self::new_with_wal(database_url, WalSettings::default()).await
+    }
+
+    /// [`WalletStorage::new_with_url`] with WAL pragmas applied to every pooled connection.
+    pub async fn new_with_wal(database_url: &str, wal: WalSettings) -> Result<Self> {
+        if is_postgres_url(database_url) {
+            return Err(anyhow::anyhow!(
+                "WalletStorage is SQLite-only; open PostgreSQL URLs through storage::open_storage"
+            ));
+        }
         // normalize sqlite URLs: accept "sqlite:" or "sqlite://"
         let mut db_url = database_url.to_string();
         if db_url.starts_with("sqlite:") && !db_url.starts_with("sqlite://") {
@@ -90,13 +307,13 @@ impl WalletStorage {
         // 配置数据库连接池（企业级配置）
         use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
         use std::str::FromStr;
-        use std::time::Duration;
         
         let connect_options = SqliteConnectOptions::from_str(&db_url)
             .map_err(|e| anyhow::anyhow!("Invalid database URL: {}", e))?
             .create_if_missing(true)  // 数据库不存在时自动创建
             .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)  // WAL模式，更好的并发性能
             .synchronous(sqlx::sqlite::SqliteSynchronous::Normal);  // 平衡性能和安全性
+        let connect_options = wal.apply(connect_options);
 
         let pool = SqlitePoolOptions::new()
             .max_connections(20)  // 最大连接数
@@ -108,8 +325,43 @@ impl WalletStorage {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to connect to database: {}", e))?;
 
-        let storage = Self { pool, is_memory };
-        storage.initialize_schema().await?;
+        let (journal_tip, _) = tokio::sync::watch::channel(0);
+        let storage = Self {
+            pool,
+            read_pool: None,
+            metrics: None,
+            is_memory,
+            journal_tip: Arc::new(journal_tip),
+            instance_id: uuid::Uuid::new_v4().to_string().into(),
+            nonce_lease: None,
+            shutdown: tokio_util::sync::CancellationToken::new(),
+            clock: system_clock(),
+            ids: random_ids(),
+            creation_fault: None,
+            wallet_cache: Arc::new(wallet_cache::WalletCache::new(WalletCacheSettings::default())),
+            query_timeouts: QueryTimeouts::default(),
+            writer_wait: Arc::default(),
+            reader_wait: Arc::default(),
+            send_locks: Arc::default(),
+        };
+        // instances of this process opening the same file migrate one after
+        // the other, so none sees a column missing that another is adding
+        let init_lock = schema_init_lock(&db_url);
+        let _init = init_lock.lock().await;
+        // ...and other processes wait for ours on a lock file next to the database
+        let _init_file = schema_init_file_lock(&db_url).await?;
+        // another process holding the write lock past the busy timeout is the
+        // one failure a later pass gets past; anything else is returned as is
+        let mut attempts = 0;
+        while let Err(e) = storage.initialize_schema().await {
+            attempts += 1;
+            if attempts == 3 || !is_lock_contention(&e) {
+                return Err(e);
+            }
+            warn!("schema initialization found the database locked, retrying: {}", e);
+            tokio::time::sleep(Duration::from_millis(50 * attempts)).await;
+        }
+        storage.journal_tip.send_replace(events_journal::last_seq(&storage.pool).await?);
 
         info!("Wallet storage initialized");
         Ok(storage)
@@ -119,6 +371,19 @@ impl WalletStorage {
         self.is_memory
     }
 
+    /// Tables from `expected` that the schema does not contain (startup preflight).
+    pub async fn missing_tables(&self, expected: &[&str]) -> Result<Vec<String>> {
+        let present: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table'")
+                .fetch_all(self.writer())
+                .await?;
+        Ok(expected
+            .iter()
+            .filter(|table| !present.iter().any(|name| name == *table))
+            .map(|table| table.to_string())
+            .collect())
+    }
+
     async fn initialize_schema(&self) -> Result<()> {
         debug!("Initializing database schema");
 
@@ -131,13 +396,16 @@ impl WalletStorage {
                 encrypted_data BLOB NOT NULL,
                 quantum_safe BOOLEAN NOT NULL,
                 created_at DATETIME NOT NULL,
-                updated_at DATETIME NOT NULL
+                updated_at DATETIME NOT NULL,
+                creation_state TEXT NOT NULL DEFAULT 'complete'
             )
             "#,
         )
-        .execute(&self.pool)
+        .execute(self.writer())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create wallets table: {}", e))?;
+        wallet_creation::init_schema(self.writer()).await?;
+        wallet_notes::init_schema(self.writer()).await?;
 
         // Transactions table
         sqlx::query(
@@ -159,9 +427,13 @@ impl WalletStorage {
             )
             "#,
         )
-        .execute(&self.pool)
+        .execute(self.writer())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create transactions table: {}", e))?;
+        tx_query::init_schema(self.writer()).await?;
+        // Rebuilds the table, dropping its indexes: runs before they are created
+        schema_migrations::init_schema(self.writer()).await?;
+        schema_migrations::key_transactions_by_wallet_id(self.writer()).await?;
 
         // Audit logs table
         sqlx::query(
@@ -177,7 +449,7 @@ impl WalletStorage {
             )
             "#,
         )
-        .execute(&self.pool)
+        .execute(self.writer())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create audit_logs table: {}", e))?;
 
@@ -191,9 +463,10 @@ impl WalletStorage {
             )
             "#,
         )
-        .execute(&self.pool)
+        .execute(self.writer())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create audit_logs_hmac table: {}", e))?;
+        audit_chain::init_schema(self.writer()).await?;
 
         // Bridge Transactions table
         sqlx::query(
@@ -215,30 +488,30 @@ impl WalletStorage {
             )
             "#,
         )
-        .execute(&self.pool)
+        .execute(self.writer())
         .await?;
 
         // Create indexes
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_wallets_name ON wallets (name)")
-            .execute(&self.pool)
+            .execute(self.writer())
             .await?;
 
         sqlx::query(
             "CREATE INDEX IF NOT EXISTS idx_transactions_wallet_id ON transactions (wallet_id)",
         )
-        .execute(&self.pool)
+        .execute(self.writer())
         .await?;
 
         sqlx::query(
             "CREATE INDEX IF NOT EXISTS idx_transactions_tx_hash ON transactions (tx_hash)",
         )
-        .execute(&self.pool)
+        .execute(self.writer())
         .await?;
 
         sqlx::query(
             "CREATE INDEX IF NOT EXISTS idx_audit_logs_wallet_id ON audit_logs (wallet_id)",
         )
-        .execute(&self.pool)
+        .execute(self.writer())
         .await?;
 
         debug!("Database schema initialized");
@@ -257,11 +530,54 @@ impl WalletStorage {
             )
             "#,
         )
-        .execute(&self.pool)
+        .execute(self.writer())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create nonces table: {}", e))?;
         // Initialize key rotation schema (non-fatal if fails? No—bubble up)
-        key_rotation::init_schema(&self.pool).await?;
+        key_rotation::init_schema(self.writer()).await?;
+        balance_snapshots::init_schema(self.writer()).await?;
+        backup_history::init_schema(self.writer()).await?;
+        events_journal::init_schema(self.writer()).await?;
+        meta_tx_relays::init_schema(self.writer()).await?;
+        wallet_tokens::init_schema(self.writer()).await?;
+        schema_migrations::move_token_credentials(self.writer()).await?;
+        multisig_policies::init_schema(self.writer()).await?;
+        signing_intents::init_schema(self.writer()).await?;
+        transaction_decisions::init_schema(self.writer()).await?;
+        distributed_locks::init_schema(self.writer()).await?;
+        wallet_networks::init_schema(self.writer()).await?;
+        user_operations::init_schema(self.writer()).await?;
+        fee_history::init_schema(self.writer()).await?;
+        approvals::init_schema(self.writer()).await?;
+        attestations::init_schema(self.writer()).await?;
+        address_book::init_schema(self.writer()).await?;
+        wallet_groups::init_schema(self.writer()).await?;
+        balance_subscriptions::init_schema(self.writer()).await?;
+        wallet_profiles::init_schema(self.writer()).await?;
+        deadman_switches::init_schema(self.writer()).await?;
+        delegations::init_schema(self.writer()).await?;
+        ledger_corrections::init_schema(self.writer()).await?;
+        request_nonces::init_schema(self.writer()).await?;
+        erasure::init_schema(self.writer()).await?;
+        cache_epochs::init_schema(self.writer()).await?;
+        reserve_reports::init_schema(self.writer()).await?;
+        operation_bundles::init_schema(self.writer()).await?;
+        process_incidents::init_schema(self.writer()).await?;
+        history_backfill::init_schema(self.writer()).await?;
+        feature_flags::init_schema(self.writer()).await?;
+        reconciliation_runs::init_schema(self.writer()).await?;
+        erc20_tokens::init_schema(self.writer()).await?;
+        nonce_lanes::init_schema(self.writer()).await?;
+        nonce_reservations::init_schema(self.writer()).await?;
+        timelocks::init_schema(self.writer()).await?;
+        sandbox::init_schema(self.writer()).await?;
+        spending_limits::init_schema(self.writer()).await?;
+        // Composite indexes backing cross-wallet triage queries
+        tx_query::init_indexes(self.writer()).await?;
+        bridge_query::init_schema(self.writer()).await?;
+        bridge_query::init_indexes(self.writer()).await?;
+        wallet_page::init_indexes(self.writer()).await?;
+        schema_migrations::record(self.writer(), self.now().timestamp()).await?;
         Ok(())
     }
 
@@ -272,14 +588,73 @@ impl WalletStorage {
         quantum_safe: bool,
     ) -> Result<()> {
         debug!("Storing wallet: {}", name);
+        self.create_wallet_atomically(
+            name,
+            encrypted_data,
+            quantum_safe,
+            &[],
+            serde_json::json!({ "name": name, "quantum_safe": quantum_safe }),
+            &format!("Wallet '{}' created", name),
+        )
+        .await?;
+        debug!("Stored wallet: {}", name);
+        Ok(())
+    }
 
-        let wallet_id = uuid::Uuid::new_v4().to_string();
-        let now = Utc::now().naive_utc();
+    /// Stores the wallet together with its per-network registrations in one
+    /// transaction: if any row fails, neither the wallet nor any network
+    /// state is left behind.
+    pub async fn create_wallet_with_networks(
+        &self,
+        name: &str,
+        encrypted_data: &[u8],
+        quantum_safe: bool,
+        networks: &[NetworkInit],
+    ) -> Result<()> {
+        debug!("Storing wallet {} with {} network(s)", name, networks.len());
+        self.create_wallet_atomically(
+            name,
+            encrypted_data,
+            quantum_safe,
+            networks,
+            serde_json::json!({
+                "name": name,
+                "quantum_safe": quantum_safe,
+                "networks": networks
+                    .iter()
+                    .map(|n| serde_json::json!({ "network": n.network, "status": n.status() }))
+                    .collect::<Vec<_>>(),
+            }),
+            &format!("Wallet '{}' created on {} network(s)", name, networks.len()),
+        )
+        .await
+    }
 
+    /// Wallet row, network rows, journal event and audit entry in one
+    /// transaction; the row turns `complete` as the last statement. A pending
+    /// ghost left under `name` by an earlier crash is discarded first.
+    async fn create_wallet_atomically(
+        &self,
+        name: &str,
+        encrypted_data: &[u8],
+        quantum_safe: bool,
+        networks: &[NetworkInit],
+        journal_payload: serde_json::Value,
+        audit_details: &str,
+    ) -> Result<()> {
+        let wallet_id = self.ids.new_id();
+        let now = self.now().naive_utc();
+
+        // takes the write lock up front, before the ghost lookup reads
+        let mut tx = self.writer().begin_with("BEGIN IMMEDIATE").await?;
+        if let Some(ghost) = wallet_creation::pending_id(&mut tx, name).await? {
+            warn!("wallet {}: discarding pending creation {} left by a crash before re-creating", name, ghost);
+            wallet_creation::discard(&mut tx, &ghost, name).await?;
+        }
         sqlx::query(
             r#"
-            INSERT INTO wallets (id, name, encrypted_data, quantum_safe, created_at, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            INSERT INTO wallets (id, name, encrypted_data, quantum_safe, created_at, updated_at, creation_state)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
             "#,
         )
         .bind(&wallet_id)
@@ -288,31 +663,153 @@ impl WalletStorage {
         .bind(quantum_safe)
         .bind(now)
         .bind(now)
-        .execute(&self.pool)
+        .bind(CREATION_PENDING)
+        .execute(&mut *tx)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to store wallet: {}", e))?;
-
-        // Log the action
-        self.log_action(
-            &wallet_id,
-            "wallet_created",
-            &format!("Wallet '{}' created", name),
-            None,
-            None,
+        if let Some(fault) = self.creation_fault_at(CreationStep::WalletRow) {
+            return Self::fail_creation(tx, fault).await;
+        }
+        for init in networks {
+            wallet_networks::upsert(&mut tx, name, init, self.now()).await?;
+        }
+        if let Some(fault) = self.creation_fault_at(CreationStep::Networks) {
+            return Self::fail_creation(tx, fault).await;
+        }
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::WALLET_CREATED,
+                entity_type: "wallet",
+                entity_id: &wallet_id,
+                payload: journal_payload,
+            },
+            self.now().timestamp(),
         )
         .await?;
+        if let Some(fault) = self.creation_fault_at(CreationStep::Journal) {
+            return Self::fail_creation(tx, fault).await;
+        }
+        self.insert_audit(&mut tx, &wallet_id, "wallet_created", audit_details, None, None).await?;
+        if let Some(fault) = self.creation_fault_at(CreationStep::Audit) {
+            return Self::fail_creation(tx, fault).await;
+        }
+        if !wallet_creation::finalize(&mut tx, &wallet_id).await? {
+            return Err(anyhow::anyhow!("Failed to store wallet: row vanished before it was finalized"));
+        }
+        cache_epochs::bump(&mut tx, cache_epochs::WALLETS, self.now().timestamp()).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store wallet: {}", e))?;
+        self.wallet_cache.flush();
+        self.journal_committed(seq);
+        Ok(())
+    }
 
-        debug!("Stored wallet: {}", name);
+    fn creation_fault_at(&self, step: CreationStep) -> Option<CreationFault> {
+        self.creation_fault.filter(|fault| fault.step() == step)
+    }
+
+    /// Ends a creation transaction at an injected fault: rolled back for
+    /// [`CreationFault::Error`], committed as-is for [`CreationFault::Crash`].
+    async fn fail_creation(tx: sqlx::Transaction<'_, sqlx::Sqlite>, fault: CreationFault) -> Result<()> {
+        if let CreationFault::Crash(_) = fault {
+            tx.commit().await?;
+        }
+        Err(anyhow::anyhow!("Injected storage fault: {:?}", fault))
+    }
+
+    /// Resolves pending wallets created at least `min_age` ago. Each one is
+    /// finalized when its blob is usable key material and every row its
+    /// creation required exists, and discarded (blob zeroed, rows deleted)
+    /// otherwise. Every decision is logged and audited.
+    pub async fn reconcile_pending_wallets(&self, min_age: chrono::Duration) -> Result<PendingWalletReport> {
+        let cutoff = (self.now() - min_age).naive_utc();
+        let pending = wallet_creation::pending_older_than(self.writer(), cutoff).await?;
+        let mut report = PendingWalletReport { checked: pending.len(), ..Default::default() };
+        for wallet in pending {
+            let mut tx = self.writer().begin().await?;
+            let mut defects = wallet_creation::missing_rows(&mut tx, &wallet.id, &wallet.name)
+                .await?
+                .into_iter()
+                .map(|row| format!("missing {}", row))
+                .collect::<Vec<_>>();
+            defects.extend(wallet_creation::blob_defect(&wallet.name, &wallet.encrypted_data));
+            let (action, reason, changed) = if defects.is_empty() {
+                let changed = wallet_creation::finalize(&mut tx, &wallet.id).await?;
+                ("finalized", "key material and required rows present".to_string(), changed)
+            } else {
+                let changed = wallet_creation::discard(&mut tx, &wallet.id, &wallet.name).await?;
+                ("discarded", defects.join("; "), changed)
+            };
+            if !changed {
+                // resolved by a concurrent creation of the same name
+                continue;
+            }
+            let details = format!("Pending wallet '{}' {}: {}", wallet.name, action, reason);
+            self.insert_audit(&mut tx, &wallet.id, &format!("wallet_creation_{}", action), &details, None, None)
+                .await?;
+            cache_epochs::bump(&mut tx, cache_epochs::WALLETS, self.now().timestamp()).await?;
+            tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to reconcile wallet {}: {}", wallet.name, e))?;
+            self.wallet_cache.flush();
+            warn!("{}", details);
+            match action {
+                "finalized" => report.finalized += 1,
+                _ => report.discarded += 1,
+            }
+            report.decisions.push(PendingWalletDecision {
+                wallet_id: wallet.id,
+                name: wallet.name,
+                action,
+                reason,
+            });
+        }
+        Ok(report)
+    }
+
+    /// Registers (or completes) networks for an existing wallet, all or nothing
+    pub async fn initialize_wallet_networks(&self, name: &str, networks: &[NetworkInit]) -> Result<()> {
+        let mut tx = self.writer().begin().await?;
+        for init in networks {
+            wallet_networks::upsert(&mut tx, name, init, self.now()).await?;
+        }
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to initialize wallet networks: {}", e))?;
+        Ok(())
+    }
+
+    /// Drops network rows and scan cursors of a wallet that lives outside this
+    /// database (non-custodial bindings are kept in users.db)
+    pub async fn forget_wallet_networks(&self, name: &str) -> Result<()> {
+        let mut tx = self.writer().begin().await?;
+        wallet_networks::delete_for_wallet(&mut tx, name).await?;
+        history_backfill::delete_for_wallet(&mut tx, name).await?;
+        tx.commit().await?;
         Ok(())
     }
 
+    pub async fn wallet_networks(&self, name: &str) -> Result<Vec<WalletNetworkRecord>> {
+        wallet_networks::for_wallet(self.writer(), name).await
+    }
+
+    pub async fn deposit_scan_cursor(&self, name: &str, network: &str) -> Result<Option<DepositScanCursor>> {
+        wallet_networks::scan_cursor(self.writer(), name, network).await
+    }
+
+    /// `wallets.id` of the wallet named `name`, which `transactions.wallet_id` holds
+    pub async fn wallet_id(&self, name: &str) -> Result<Option<String>> {
+        sqlx::query_scalar("SELECT id FROM wallets WHERE name = ?1")
+            .bind(name)
+            .fetch_optional(self.writer())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to find wallet: {}", e))
+    }
+
     pub async fn load_wallet(&self, name: &str) -> Result<(Vec<u8>, bool)> {
         debug!("Loading wallet: {}", name);
 
         let row =
-            sqlx::query("SELECT id, encrypted_data, quantum_safe FROM wallets WHERE name = ?1")
+            sqlx::query("SELECT id, encrypted_data, quantum_safe FROM wallets WHERE name = ?1 AND creation_state = ?2")
                 .bind(name)
-                .fetch_optional(&self.pool)
+                .bind(CREATION_COMPLETE)
+                .fetch_optional(self.writer())
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to load wallet: {}", e))?;
 
@@ -339,20 +836,42 @@ impl WalletStorage {
         }
     }
 
+    /// Lists every wallet, newest first. Walks the keyset pages internally;
+    /// prefer [`WalletStorage::list_wallets_page`] when the caller can stream.
     pub async fn list_wallets(&self) -> Result<Vec<WalletMetadata>> {
+        self.collect_wallets(self.reader()).await
+    }
+
+    async fn collect_wallets(&self, pool: &SqlitePool) -> Result<Vec<WalletMetadata>> {
         debug!("Listing all wallets");
 
-        let wallets = sqlx::query_as::<_, WalletMetadata>(
-                "SELECT id, name, quantum_safe, created_at, updated_at FROM wallets ORDER BY created_at DESC"
-            )
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to list wallets: {}", e))?;
+        let mut wallets = Vec::new();
+        let mut cursor: Option<WalletCursor> = None;
+        loop {
+            let page = wallet_page::list_page(pool, cursor.as_ref(), MAX_WALLET_PAGE_SIZE).await?;
+            wallets.extend(
+                page.wallets.into_iter().map(|w| Arc::try_unwrap(w).unwrap_or_else(|w| (*w).clone())),
+            );
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
 
         debug!("Listed {} wallets", wallets.len());
         Ok(wallets)
     }
 
+    /// One keyset page of wallet metadata (newest first), `limit` capped at
+    /// [`MAX_WALLET_PAGE_SIZE`].
+    pub async fn list_wallets_page(
+        &self,
+        cursor: Option<&WalletCursor>,
+        limit: usize,
+    ) -> Result<WalletPage> {
+        wallet_page::list_page(self.reader(), cursor, limit).await
+    }
+
     pub async fn update_wallet_encrypted_data(
         &self,
         name: &str,
@@ -360,7 +879,8 @@ impl WalletStorage {
     ) -> Result<()> {
         debug!("Updating wallet encrypted_data: {}", name);
 
-        let now = Utc::now().naive_utc();
+        let now = self.now().naive_utc();
+        let mut tx = self.writer().begin().await?;
         let result = sqlx::query(
             r#"
             UPDATE wallets SET encrypted_data = ?1, updated_at = ?2 WHERE name = ?3
@@ -369,13 +889,16 @@ impl WalletStorage {
         .bind(encrypted_data)
         .bind(now)
         .bind(name)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to update wallet: {}", e))?;
 
         if result.rows_affected() == 0 {
             return Err(anyhow::anyhow!("Wallet not found: {}", name));
         }
+        cache_epochs::bump(&mut tx, cache_epochs::WALLETS, self.now().timestamp()).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to update wallet: {}", e))?;
+        self.wallet_cache.flush();
 
         Ok(())
     }
@@ -390,7 +913,7 @@ impl WalletStorage {
         // Get wallet ID first
         let row = sqlx::query("SELECT id FROM wallets WHERE name = ?1")
             .bind(name)
-            .fetch_optional(&self.pool)
+            .fetch_optional(self.writer())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to find wallet: {}", e))?;
 
@@ -402,15 +925,43 @@ impl WalletStorage {
         };
 
         // Delete wallet
+        let mut tx = self.writer().begin().await?;
         let result = sqlx::query("DELETE FROM wallets WHERE name = ?1")
             .bind(name)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to delete wallet: {}", e))?;
 
         if result.rows_affected() == 0 {
             return Err(anyhow::anyhow!("Wallet not found: {}", name));
         }
+        wallet_networks::delete_for_wallet(&mut tx, name).await?;
+        history_backfill::delete_for_wallet(&mut tx, name).await?;
+        wallet_groups::delete_memberships(&mut tx, name).await?;
+        deadman_switches::delete(&mut tx, name).await?;
+        delegations::delete_for_wallet(&mut tx, name).await?;
+        address_book::delete_for_wallet(&mut tx, name).await?;
+        erc20_tokens::delete_for_wallet(&mut tx, name).await?;
+        timelocks::delete_for_wallet(&mut tx, name).await?;
+        spending_limits::delete_for_wallet(&mut tx, name).await?;
+        if feature_flags::delete_for_wallet(&mut tx, name).await? {
+            cache_epochs::bump(&mut tx, cache_epochs::FEATURE_FLAGS, self.now().timestamp()).await?;
+        }
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::WALLET_DELETED,
+                entity_type: "wallet",
+                entity_id: &wallet_id,
+                payload: serde_json::json!({ "name": name }),
+            },
+            self.now().timestamp(),
+        )
+        .await?;
+        cache_epochs::bump(&mut tx, cache_epochs::WALLETS, self.now().timestamp()).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to delete wallet: {}", e))?;
+        self.wallet_cache.flush();
+        self.journal_committed(seq);
 
         // Log the action
         self.log_action(
@@ -427,15 +978,56 @@ impl WalletStorage {
     }
 
     pub async fn store_transaction(&self, tx_data: &TransactionRecord) -> Result<()> {
+        self.store_transaction_with_decision(tx_data, None).await
+    }
+
+    /// [`Self::store_transaction`], committing the decision context (redacted
+    /// JSON) and its `TRANSACTION_DECIDED` event in the same transaction.
+    pub async fn store_transaction_with_decision(
+        &self,
+        tx_data: &TransactionRecord,
+        decision: Option<&str>,
+    ) -> Result<()> {
         debug!("Storing transaction: {}", tx_data.tx_hash);
 
+        let seq = self
+            .timed(DbPool::Writer, "transactions.insert", QueryClass::Hot, |mut conn| async move {
+                let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+                let mut seq = self.insert_transaction(&mut tx, tx_data).await?;
+                if let Some(decision) = decision {
+                    seq = self.insert_decision(&mut tx, tx_data, decision).await?;
+                }
+                tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store transaction: {}", e))?;
+                Ok(seq)
+            })
+            .await?;
+        self.journal_committed(seq);
+
+        debug!("Transaction stored: {}", tx_data.tx_hash);
+        Ok(())
+    }
+
+    /// Inserts the row and its `TRANSACTION_CREATED` journal event; returns the journal sequence.
+    async fn insert_transaction(&self, conn: &mut sqlx::SqliteConnection, tx_data: &TransactionRecord) -> Result<i64> {
+        self.insert_transaction_with(conn, tx_data, tx_data.status == "confirmed").await
+    }
+
+    /// [`Self::insert_transaction`], with the caller deciding whether the row
+    /// is a send that counts toward the wallet's profile.
+    async fn insert_transaction_with(
+        &self,
+        conn: &mut sqlx::SqliteConnection,
+        tx_data: &TransactionRecord,
+        profile: bool,
+    ) -> Result<i64> {
         // Calculate integrity hash
         let integrity_hash = Self::calculate_transaction_integrity_hash(tx_data);
 
         sqlx::query(
                 r#"
-            INSERT INTO transactions (id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at, confirmed_at, integrity_hash)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            INSERT INTO transactions (id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at, confirmed_at, integrity_hash,
+                fee_actual, block_number)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
             "#
             )
             .bind(&tx_data.id)
@@ -450,25 +1042,139 @@ impl WalletStorage {
             .bind(tx_data.created_at)
             .bind(tx_data.confirmed_at)
             .bind(integrity_hash)
-            .execute(&self.pool).await
+            .bind(&tx_data.fee_actual)
+            .bind(tx_data.block_number)
+            .execute(&mut *conn).await
             .map_err(|e| anyhow::anyhow!("Failed to store transaction: {}", e))?;
+        if profile {
+            self.fold_into_profile(conn, tx_data).await?;
+        }
+        events_journal::append(
+            conn,
+            &NewJournalEvent {
+                event_type: events_journal::TRANSACTION_CREATED,
+                entity_type: "transaction",
+                entity_id: &tx_data.id,
+                payload: serde_json::json!({
+                    "wallet_id": tx_data.wallet_id,
+                    "tx_hash": tx_data.tx_hash,
+                    "network": tx_data.network,
+                    "from_address": tx_data.from_address,
+                    "to_address": tx_data.to_address,
+                    "amount": tx_data.amount,
+                    "fee": tx_data.fee,
+                    "status": tx_data.status,
+                }),
+            },
+            self.now().timestamp(),
+        )
+        .await
+    }
 
-        debug!("Transaction stored: {}", tx_data.tx_hash);
-        Ok(())
+    async fn insert_decision(
+        &self,
+        conn: &mut sqlx::SqliteConnection,
+        tx_data: &TransactionRecord,
+        decision: &str,
+    ) -> Result<i64> {
+        let now = self.now().timestamp();
+        transaction_decisions::insert(conn, &tx_data.id, decision, now).await?;
+        let decision: serde_json::Value =
+            serde_json::from_str(decision).map_err(|e| anyhow::anyhow!("Malformed decision context: {}", e))?;
+        events_journal::append(
+            conn,
+            &NewJournalEvent {
+                event_type: events_journal::TRANSACTION_DECIDED,
+                entity_type: "transaction",
+                entity_id: &tx_data.id,
+                payload: serde_json::json!({
+                    "wallet_id": tx_data.wallet_id,
+                    "tx_hash": tx_data.tx_hash,
+                    "decision": decision,
+                }),
+            },
+            now,
+        )
+        .await
+    }
+
+    /// Decision context recorded with transaction `id`, if any.
+    pub async fn transaction_decision(&self, id: &str) -> Result<Option<serde_json::Value>> {
+        let Some(decision) = transaction_decisions::get(self.reader(), id).await? else {
+            return Ok(None);
+        };
+        Ok(Some(serde_json::from_str(&decision).map_err(|e| anyhow::anyhow!("Malformed decision of {}: {}", id, e))?))
+    }
+
+    /// Counts a confirmed send in its wallet's behavioral profile.
+    async fn fold_into_profile(&self, conn: &mut sqlx::SqliteConnection, tx: &TransactionRecord) -> Result<()> {
+        let Some(amount) = wallet_profiles::feature_amount(&tx.network, &tx.amount) else {
+            warn!("transaction {} has unreadable amount {:?}; profile not updated", tx.id, tx.amount);
+            return Ok(());
+        };
+        // profiles are keyed by wallet name
+        let Some(wallet_name): Option<String> = sqlx::query_scalar("SELECT name FROM wallets WHERE id = ?1")
+            .bind(&tx.wallet_id)
+            .fetch_optional(&mut *conn)
+            .await?
+        else {
+            return Ok(());
+        };
+        let sent_at = tx.created_at.timestamp();
+        wallet_profiles::apply_send(conn, &wallet_name, &tx.network, &tx.to_address, amount, sent_at, self.now().timestamp())
+            .await
+    }
+
+    /// `pending` transactions of the wallet named `wallet_name` on `network`
+    /// created at or after `since`, newest first.
+    pub async fn pending_transactions_since(
+        &self,
+        wallet_name: &str,
+        network: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<TransactionRecord>> {
+        let transactions = sqlx::query_as::<_, TransactionRecord>(
+            r#"
+            SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at, confirmed_at, integrity_hash,
+                   fee_actual, block_number
+            FROM transactions
+            WHERE wallet_id = (SELECT id FROM wallets WHERE name = ?1) AND network = ?2 AND status = 'pending'
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(wallet_name)
+        .bind(network)
+        .fetch_all(self.writer())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get pending transactions: {}", e))?;
+        Ok(transactions.into_iter().filter(|tx| tx.created_at >= since).collect())
     }
 
     pub async fn get_wallet_transactions(&self, wallet_id: &str) -> Result<Vec<TransactionRecord>> {
+        self.get_wallet_transactions_with(wallet_id, ReadConsistency::Eventual).await
+    }
+
+    /// [`WalletStorage::get_wallet_transactions`] with an explicit freshness
+    /// requirement; pass `AfterSeq(self.write_watermark())` to read back a
+    /// transaction stored earlier in the same request.
+    pub async fn get_wallet_transactions_with(
+        &self,
+        wallet_id: &str,
+        consistency: ReadConsistency,
+    ) -> Result<Vec<TransactionRecord>> {
         debug!("Getting transactions for wallet: {}", wallet_id);
+        let pool = self.reader_for(consistency).await?;
 
         let transactions = sqlx::query_as::<_, TransactionRecord>(
                 r#"
-            SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at, confirmed_at, integrity_hash
+            SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at, confirmed_at, integrity_hash,
+                   fee_actual, block_number
             FROM transactions 
             WHERE wallet_id = ?1 
             ORDER BY created_at DESC
             "#
             ).bind(wallet_id)
-            .fetch_all(&self.pool).await
+            .fetch_all(pool).await
             .map_err(|e| anyhow::anyhow!("Failed to get transactions: {}", e))?;
 
         // Verify integrity of each transaction
@@ -480,6 +1186,28 @@ impl WalletStorage {
         Ok(transactions)
     }
 
+    /// Most recent record with this hash (case-insensitive), integrity-checked.
+    pub async fn transaction_by_hash(&self, tx_hash: &str) -> Result<Option<TransactionRecord>> {
+        let transaction = sqlx::query_as::<_, TransactionRecord>(
+            r#"
+            SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at, confirmed_at, integrity_hash,
+                   fee_actual, block_number
+            FROM transactions
+            WHERE tx_hash = ?1 COLLATE NOCASE
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(tx_hash)
+        .fetch_optional(self.reader())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get transaction: {}", e))?;
+        if let Some(tx) = &transaction {
+            Self::verify_transaction_integrity(tx)?;
+        }
+        Ok(transaction)
+    }
+
     pub async fn log_action(
         &self,
         wallet_id: &str,
@@ -487,6 +1215,38 @@ impl WalletStorage {
         details: &str,
         ip_address: Option<&str>,
         user_agent: Option<&str>,
+    ) -> Result<()> {
+        let mut tx = self.writer().begin().await?;
+        self.insert_audit(&mut tx, wallet_id, action, details, ip_address, user_agent).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to log action: {}", e))?;
+        Ok(())
+    }
+
+    /// Audit row plus its HMAC on `conn`, inside the caller's transaction
+    async fn insert_audit(
+        &self,
+        conn: &mut sqlx::SqliteConnection,
+        wallet_id: &str,
+        action: &str,
+        details: &str,
+        ip_address: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<()> {
+        let now = self.now().naive_utc();
+        self.insert_audit_at(conn, wallet_id, action, details, ip_address, user_agent, now).await
+    }
+
+    /// [`WalletStorage::insert_audit`] with an explicit timestamp (rows copied from another database)
+    #[allow(clippy::too_many_arguments)]
+    async fn insert_audit_at(
+        &self,
+        conn: &mut sqlx::SqliteConnection,
+        wallet_id: &str,
+        action: &str,
+        details: &str,
+        ip_address: Option<&str>,
+        user_agent: Option<&str>,
+        created_at: NaiveDateTime,
     ) -> Result<()> {
         // Insert audit row and capture inserted row id from this statement result
         let res = sqlx::query(
@@ -500,11 +1260,13 @@ impl WalletStorage {
         .bind(details)
         .bind(ip_address)
         .bind(user_agent)
-        .bind(Utc::now().naive_utc())
-        .execute(&self.pool)
+        .bind(created_at)
+        .execute(&mut *conn)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to log action: {}", e))?;
         let audit_id: i64 = res.last_insert_rowid();
+        // The insert holds the write lock, so no other row can slip in between the link and this row
+        let prev_mac = audit_chain::prev_mac(&mut *conn, audit_id).await?;
 
         // Compute HMAC over the log content for integrity; key comes from WALLET_ENC_KEY as stable KEK (or test-env).
         let mac = Self::compute_audit_mac(
@@ -514,16 +1276,18 @@ impl WalletStorage {
             details,
             ip_address,
             user_agent,
+            prev_mac.as_deref(),
         )?;
 
         sqlx::query(
             r#"
-            INSERT INTO audit_logs_hmac (audit_id, mac) VALUES (?1, ?2)
+            INSERT INTO audit_logs_hmac (audit_id, mac, prev_mac) VALUES (?1, ?2, ?3)
             "#,
         )
         .bind(audit_id)
         .bind(mac)
-        .execute(&self.pool)
+        .bind(prev_mac)
+        .execute(&mut *conn)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to store audit mac: {}", e))?;
 
@@ -543,22 +1307,77 @@ impl WalletStorage {
             query_builder = query_builder.bind(param);
         }
 
+        // MACs are read from the same pool as the rows they cover
+        let pool = self.reader();
         let logs = query_builder
             .try_map(|row: sqlx::sqlite::SqliteRow| AuditLog::from_row(&row))
-            .fetch_all(&self.pool)
+            .fetch_all(pool)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to get audit logs: {}", e))?;
 
         // Verify HMAC integrity for each audit log row
         for log in &logs {
-            if let Err(e) = self.verify_audit_log_mac(log).await {
+            if let Err(e) = Self::verify_audit_log_mac(pool, log).await {
                 return Err(anyhow::anyhow!("Audit log integrity failed for id {}: {}", log.id, e));
             }
         }
 
+        // Row MACs miss deleted rows; the chain does not
+        let chain = self.verify_audit_chain().await?;
+        if let (Some(id), Some(reason)) = (chain.broken_at, chain.reason) {
+            return Err(anyhow::anyhow!("Audit log chain broken at id {}: {}", id, reason));
+        }
+
         Ok(logs)
     }
 
+    /// Walks the whole audit log from its genesis entry and reports the first
+    /// entry whose MAC or previous-MAC link does not verify. Reads the same pool
+    /// as [`WalletStorage::get_audit_logs`].
+    pub async fn verify_audit_chain(&self) -> Result<AuditChainReport> {
+        self.timed(DbPool::Reader, "audit_logs.verify_chain", QueryClass::Analytics, |mut conn| async move {
+            audit_chain::verify(&mut conn).await
+        })
+        .await
+    }
+
+    /// Newest-first page of audit rows with `id < before_id`, verified like
+    /// [`WalletStorage::get_audit_logs`]. The second value is the `before_id`
+    /// of the next page, `None` on the last one.
+    pub async fn audit_logs_page(
+        &self,
+        wallet_id: Option<&str>,
+        before_id: Option<i64>,
+        limit: usize,
+    ) -> Result<(Vec<AuditLog>, Option<i64>)> {
+        let mut qb: sqlx::QueryBuilder<sqlx::Sqlite> = sqlx::QueryBuilder::new("SELECT * FROM audit_logs WHERE 1 = 1");
+        if let Some(id) = wallet_id {
+            qb.push(" AND wallet_id = ").push_bind(id);
+        }
+        if let Some(before) = before_id {
+            qb.push(" AND id < ").push_bind(before);
+        }
+        qb.push(" ORDER BY id DESC LIMIT ").push_bind((limit + 1) as i64);
+
+        let pool = self.reader();
+        let mut logs = qb
+            .build()
+            .try_map(|row: sqlx::sqlite::SqliteRow| AuditLog::from_row(&row))
+            .fetch_all(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get audit logs: {}", e))?;
+        let has_more = logs.len() > limit;
+        logs.truncate(limit);
+
+        for log in &logs {
+            if let Err(e) = Self::verify_audit_log_mac(pool, log).await {
+                return Err(anyhow::anyhow!("Audit log integrity failed for id {}: {}", log.id, e));
+            }
+        }
+        let next = if has_more { logs.last().map(|l| l.id) } else { None };
+        Ok((logs, next))
+    }
+
     /// Calculate integrity hash for transaction data to prevent tampering
     fn calculate_transaction_integrity_hash(tx: &TransactionRecord) -> String {
         let mut hasher = Sha256::new();
@@ -575,6 +1394,15 @@ impl WalletStorage {
         if let Some(confirmed_at) = tx.confirmed_at {
             hasher.update(confirmed_at.timestamp().to_le_bytes());
         }
+        // receipt fields are only hashed once set, so older hashes still verify
+        if let Some(fee_actual) = &tx.fee_actual {
+            hasher.update(b"fee_actual");
+            hasher.update(fee_actual.as_bytes());
+        }
+        if let Some(block_number) = tx.block_number {
+            hasher.update(b"block_number");
+            hasher.update(block_number.to_le_bytes());
+        }
         format!("{:x}", hasher.finalize())
     }
 
@@ -595,8 +1423,8 @@ impl WalletStorage {
     // --- Audit log MAC helpers ---
     fn load_audit_hmac_key() -> Result<[u8; 32]> {
         use zeroize::Zeroize;
-        let b64 = std::env::var("WALLET_ENC_KEY")
-            .map_err(|_| anyhow::anyhow!("WALLET_ENC_KEY not set for audit MAC"))?;
+        let b64 = crate::security::env_manager::secret_backend::master_key_encoded()
+            .map_err(|e| anyhow::anyhow!("{} (audit MAC)", e))?;
         let mut raw = base64::engine::general_purpose::STANDARD
             .decode(b64.trim())
             .map_err(|_| anyhow::anyhow!("WALLET_ENC_KEY must be base64(32)"))?;
@@ -623,6 +1451,7 @@ impl WalletStorage {
         details: &str,
         ip_address: Option<&str>,
         user_agent: Option<&str>,
+        prev_mac: Option<&str>,
     ) -> Result<String> {
         use hmac::{Hmac, Mac};
         use sha2::Sha256;
@@ -640,13 +1469,18 @@ impl WalletStorage {
         if let Some(ua) = user_agent {
             mac.update(ua.as_bytes());
         }
+        // Tagged so rows written before the chain keep their MAC
+        if let Some(prev) = prev_mac {
+            mac.update(b"|prev_mac:");
+            mac.update(prev.as_bytes());
+        }
         Ok(hex::encode(mac.finalize().into_bytes()))
     }
 
-    async fn verify_audit_log_mac(&self, log: &AuditLog) -> Result<()> {
-        let row = sqlx::query("SELECT mac FROM audit_logs_hmac WHERE audit_id = ?1")
+    async fn verify_audit_log_mac(pool: &SqlitePool, log: &AuditLog) -> Result<()> {
+        let row = sqlx::query("SELECT mac, prev_mac FROM audit_logs_hmac WHERE audit_id = ?1")
             .bind(log.id)
-            .fetch_optional(&self.pool)
+            .fetch_optional(pool)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to load audit mac: {}", e))?;
 
@@ -654,6 +1488,7 @@ impl WalletStorage {
             return Err(anyhow::anyhow!("Missing audit mac"));
         };
         let stored_mac: String = row.get::<String, _>("mac");
+        let prev_mac: Option<String> = row.get("prev_mac");
         let calc = Self::compute_audit_mac(
             log.id,
             log.wallet_id.as_deref().unwrap_or(""),
@@ -661,6 +1496,7 @@ impl WalletStorage {
             log.details.as_deref().unwrap_or(""),
             log.ip_address.as_deref(),
             log.user_agent.as_deref(),
+            prev_mac.as_deref(),
         )?;
         if stored_mac != calc {
             return Err(anyhow::anyhow!("MAC mismatch"));
@@ -669,103 +1505,3036 @@ impl WalletStorage {
     }
 }
 
-// Key rotation persistence API
+// Cross-wallet transaction triage API
 impl WalletStorage {
-    pub async fn rotation_upsert_label(
+    /// Query transactions across all wallets. Reads only the transactions table.
+    pub async fn query_transactions(
         &self,
-        label: &str,
-        current_version: i64,
-        current_id: Option<&str>,
-    ) -> Result<()> {
-        key_rotation::upsert_label(&self.pool, label, current_version, current_id).await
-    }
-
-    pub async fn rotation_insert_version(
-        &self,
-        label: &str,
-        version: i64,
-        key_id: &str,
-    ) -> Result<()> {
-        key_rotation::insert_version(&self.pool, label, version, key_id).await
-    }
-
-    pub async fn rotation_mark_retired(&self, label: &str, version: i64) -> Result<()> {
-        key_rotation::mark_retired(&self.pool, label, version).await
+        filter: &TransactionFilter,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<TransactionRecord>, usize)> {
+        // admin exports page through every match
+        let (rows, total) = self
+            .timed(DbPool::Reader, "transactions.query", QueryClass::Analytics, |mut conn| async move {
+                tx_query::query(&mut conn, filter, offset, limit).await
+            })
+            .await?;
+        for tx in &rows {
+            Self::verify_transaction_integrity(tx)?;
+        }
+        Ok((rows, total))
     }
 
-    pub async fn rotation_inc_usage(&self, label: &str, version: i64) -> Result<()> {
-        key_rotation::inc_usage(&self.pool, label, version).await
+    /// `EXPLAIN QUERY PLAN` output for the query `filter` would run (diagnostics/tests).
+    pub async fn explain_transaction_query(&self, filter: &TransactionFilter) -> Result<Vec<String>> {
+        tx_query::explain(self.reader(), filter).await
     }
 
-    pub async fn rotation_get_label(
+    /// Newest-first page of the transactions of `wallet_ids`, merged; see
+    /// [`tx_query::wallet_set_page`].
+    pub async fn wallet_set_transactions(
         &self,
-        label: &str,
-    ) -> Result<Option<key_rotation::KeyLabelRecord>> {
-        key_rotation::get_label(&self.pool, label).await
+        wallet_ids: &[String],
+        cursor: Option<&TransactionCursor>,
+        limit: usize,
+    ) -> Result<(Vec<TransactionRecord>, Option<TransactionCursor>)> {
+        let (rows, next) = tx_query::wallet_set_page(self.reader(), wallet_ids, cursor, limit).await?;
+        for tx in &rows {
+            Self::verify_transaction_integrity(tx)?;
+        }
+        Ok((rows, next))
     }
 
-    pub async fn rotation_get_version(
+    /// Update a transaction's status, re-sealing its integrity hash.
+    pub async fn update_transaction_status(
         &self,
-        label: &str,
-        version: i64,
-    ) -> Result<Option<key_rotation::KeyVersionRecord>> {
-        key_rotation::get_version(&self.pool, label, version).await
-    }
-}
-
-// Bridge Transaction Storage
-impl WalletStorage {
-    pub async fn store_bridge_transaction(&self, tx: &BridgeTransaction) -> Result<()> {
-        let status_str = serde_json::to_string(&tx.status)?;
-        sqlx::query(
+        id: &str,
+        status: &str,
+        confirmed_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let mut tx = sqlx::query_as::<_, TransactionRecord>(
             r#"
-            INSERT INTO bridge_transactions (id, from_wallet, from_chain, to_chain, token, amount, status, source_tx_hash, destination_tx_hash, created_at, updated_at, fee_amount, estimated_completion_time)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at, confirmed_at, integrity_hash,
+                   fee_actual, block_number
+            FROM transactions WHERE id = ?1
             "#,
         )
-        .bind(&tx.id)
-        .bind(&tx.from_wallet)
-        .bind(&tx.from_chain)
-        .bind(&tx.to_chain)
-        .bind(&tx.token)
-        .bind(&tx.amount)
-        .bind(status_str)
-        .bind(&tx.source_tx_hash)
-        .bind(&tx.destination_tx_hash)
-        .bind(tx.created_at)
-        .bind(tx.updated_at)
-        .bind(&tx.fee_amount)
-        .bind(tx.estimated_completion_time)
-        .execute(&self.pool)
-        .await?;
-        Ok(())
-    }
-
-    pub async fn get_bridge_transaction(&self, id: &str) -> Result<BridgeTransaction> {
-        let row = sqlx::query("SELECT * FROM bridge_transactions WHERE id = ?1")
-            .bind(id)
-            .fetch_one(&self.pool)
-            .await?;
+        .bind(id)
+        .fetch_optional(self.writer())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load transaction: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("Transaction not found: {}", id))?;
 
-        let status_str: String = row.get("status");
-        let status: BridgeTransactionStatus = serde_json::from_str(&status_str)?;
+        Self::verify_transaction_integrity(&tx)?;
+        let previous_status = std::mem::replace(&mut tx.status, status.to_string());
+        tx.confirmed_at = confirmed_at.or(tx.confirmed_at);
+        let integrity_hash = Self::calculate_transaction_integrity_hash(&tx);
 
-        let tx = BridgeTransaction {
-            id: row.get("id"),
-            from_wallet: row.get("from_wallet"),
-            from_chain: row.get("from_chain"),
-            to_chain: row.get("to_chain"),
-            token: row.get("token"),
-            amount: row.get("amount"),
-            status,
-            source_tx_hash: row.get("source_tx_hash"),
-            destination_tx_hash: row.get("destination_tx_hash"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-            fee_amount: row.get("fee_amount"),
-            estimated_completion_time: row.get("estimated_completion_time"),
+        let mut db_tx = self.writer().begin().await?;
+        sqlx::query(
+            "UPDATE transactions SET status = ?1, confirmed_at = ?2, integrity_hash = ?3 WHERE id = ?4",
+        )
+        .bind(&tx.status)
+        .bind(tx.confirmed_at)
+        .bind(integrity_hash)
+        .bind(id)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update transaction status: {}", e))?;
+        if previous_status != "confirmed" && tx.status == "confirmed" {
+            self.fold_into_profile(&mut db_tx, &tx).await?;
+        }
+        let seq = if previous_status != tx.status {
+            Some(
+                events_journal::append(
+                    &mut db_tx,
+                    &NewJournalEvent {
+                        event_type: events_journal::TRANSACTION_STATUS_CHANGED,
+                        entity_type: "transaction",
+                        entity_id: id,
+                        payload: serde_json::json!({
+                            "wallet_id": tx.wallet_id,
+                            "tx_hash": tx.tx_hash,
+                            "network": tx.network,
+                            "from_status": previous_status,
+                            "to_status": tx.status,
+                            "confirmed_at": tx.confirmed_at,
+                        }),
+                    },
+                    self.now().timestamp(),
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+        // expired was reported as never happening; mined after all, it needs booking
+        let mut correction_seq = None;
+        if previous_status == "expired" && matches!(tx.status.as_str(), "confirmed" | "failed") {
+            let correction_id = ledger_corrections::insert(
+                &mut db_tx,
+                &tx,
+                &previous_status,
+                "mined after expiry",
+                self.now().timestamp(),
+            )
+            .await?;
+            warn!(
+                "transaction {} ({}) {} after expiring; ledger correction {}",
+                id, tx.tx_hash, tx.status, correction_id
+            );
+            correction_seq = Some(
+                events_journal::append(
+                    &mut db_tx,
+                    &NewJournalEvent {
+                        event_type: events_journal::LEDGER_CORRECTED,
+                        entity_type: "transaction",
+                        entity_id: id,
+                        payload: serde_json::json!({
+                            "correction_id": correction_id,
+                            "wallet_id": tx.wallet_id,
+                            "tx_hash": tx.tx_hash,
+                            "network": tx.network,
+                            "from_status": previous_status,
+                            "to_status": tx.status,
+                            "amount": tx.amount,
+                            "fee": tx.fee,
+                        }),
+                    },
+                    self.now().timestamp(),
+                )
+                .await?,
+            );
+        }
+        db_tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to update transaction status: {}", e))?;
+        if let Some(seq) = correction_seq.or(seq) {
+            self.journal_committed(seq);
+        }
+
+        Ok(())
+    }
+
+    /// `pending` transactions on `network` created before `created_before`, oldest first.
+    pub async fn stale_pending_transactions(
+        &self,
+        network: &str,
+        created_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<TransactionRecord>> {
+        let transactions = sqlx::query_as::<_, TransactionRecord>(
+            r#"
+            SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at, confirmed_at, integrity_hash,
+                   fee_actual, block_number
+            FROM transactions
+            WHERE network = ?1 AND status = 'pending' AND created_at < ?2
+            ORDER BY created_at
+            LIMIT ?3
+            "#,
+        )
+        .bind(network)
+        .bind(created_before)
+        .bind(limit)
+        .fetch_all(self.writer())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get stale pending transactions: {}", e))?;
+        for tx in &transactions {
+            Self::verify_transaction_integrity(tx)?;
+        }
+        Ok(transactions)
+    }
+
+    /// Moves a `pending` transaction to the terminal `expired` status and
+    /// journals `transaction.expired` with it. `false` if it was no longer
+    /// pending, e.g. confirmed concurrently.
+    pub async fn expire_transaction(&self, id: &str, reason: &str) -> Result<bool> {
+        let Some(mut tx) = sqlx::query_as::<_, TransactionRecord>(
+            r#"
+            SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at, confirmed_at, integrity_hash,
+                   fee_actual, block_number
+            FROM transactions WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.writer())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load transaction: {}", e))?
+        else {
+            return Ok(false);
+        };
+        Self::verify_transaction_integrity(&tx)?;
+        if tx.status != "pending" {
+            return Ok(false);
+        }
+        tx.status = "expired".to_string();
+        let integrity_hash = Self::calculate_transaction_integrity_hash(&tx);
+        let now = self.now();
+
+        let mut db_tx = self.writer().begin().await?;
+        let updated = sqlx::query(
+            "UPDATE transactions SET status = ?1, integrity_hash = ?2 WHERE id = ?3 AND status = 'pending'",
+        )
+        .bind(&tx.status)
+        .bind(integrity_hash)
+        .bind(id)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to expire transaction: {}", e))?;
+        if updated.rows_affected() == 0 {
+            return Ok(false);
+        }
+        events_journal::append(
+            &mut db_tx,
+            &NewJournalEvent {
+                event_type: events_journal::TRANSACTION_STATUS_CHANGED,
+                entity_type: "transaction",
+                entity_id: id,
+                payload: serde_json::json!({
+                    "wallet_id": tx.wallet_id,
+                    "tx_hash": tx.tx_hash,
+                    "network": tx.network,
+                    "from_status": "pending",
+                    "to_status": tx.status,
+                    "confirmed_at": tx.confirmed_at,
+                }),
+            },
+            now.timestamp(),
+        )
+        .await?;
+        let seq = events_journal::append(
+            &mut db_tx,
+            &NewJournalEvent {
+                event_type: events_journal::TRANSACTION_EXPIRED,
+                entity_type: "transaction",
+                entity_id: id,
+                payload: serde_json::json!({
+                    "wallet_id": tx.wallet_id,
+                    "tx_hash": tx.tx_hash,
+                    "network": tx.network,
+                    "from_address": tx.from_address,
+                    "to_address": tx.to_address,
+                    "amount": tx.amount,
+                    "pending_secs": (now - tx.created_at).num_seconds().max(0),
+                    "reason": reason,
+                }),
+            },
+            now.timestamp(),
+        )
+        .await?;
+        db_tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to expire transaction: {}", e))?;
+        self.journal_committed(seq);
+        Ok(true)
+    }
+
+    /// Corrections booked for transactions mined after expiring, oldest first.
+    pub async fn ledger_corrections(&self, wallet_id: Option<&str>, limit: i64) -> Result<Vec<LedgerCorrection>> {
+        ledger_corrections::list(self.reader(), wallet_id, limit).await
+    }
+}
+
+// Balance snapshot API
+impl WalletStorage {
+    /// Records a snapshot unless the balance is unchanged since the last one.
+    pub async fn record_balance_snapshot(&self, snapshot: &BalanceSnapshot) -> Result<bool> {
+        balance_snapshots::record_if_changed(self.writer(), snapshot).await
+    }
+
+    /// Balance series over `[from, to)`, gaps filled with the previous value.
+    pub async fn balance_history(
+        &self,
+        wallet_id: &str,
+        network: &str,
+        token: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        resolution: SnapshotResolution,
+    ) -> Result<Vec<BalancePoint>> {
+        let step = resolution.step_secs();
+        let from = from.timestamp().div_euclid(step) * step;
+        let to = to.timestamp();
+        let pool = self.reader();
+        let seed = balance_snapshots::latest_before(pool, wallet_id, network, token, from).await?;
+        let rows = balance_snapshots::range(pool, wallet_id, network, token, from, to).await?;
+        Ok(balance_snapshots::build_series(seed.as_ref(), &rows, from, to, resolution))
+    }
+
+    /// Thins snapshots older than `cutoff` to one per day; returns rows deleted.
+    pub async fn downsample_balance_snapshots(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        balance_snapshots::downsample(self.writer(), cutoff.timestamp()).await
+    }
+
+    /// Records the `base`/`quote` price seen by a snapshot cycle.
+    pub async fn record_price_snapshot(&self, base: &str, quote: &str, price: &str, captured_at: i64) -> Result<()> {
+        balance_snapshots::insert_price(self.writer(), base, quote, price, captured_at).await
+    }
+
+    /// Latest recorded price at or before `at`, if one was captured within
+    /// `max_age` of it.
+    pub async fn price_snapshot_at(
+        &self,
+        base: &str,
+        quote: &str,
+        at: DateTime<Utc>,
+        max_age: chrono::Duration,
+    ) -> Result<Option<String>> {
+        let at = at.timestamp();
+        balance_snapshots::price_at(self.reader(), base, quote, at, at - max_age.num_seconds()).await
+    }
+}
+
+// Fee history API
+impl WalletStorage {
+    /// Records the fee caps of a broadcast send; a second call for the same
+    /// hash is ignored.
+    pub async fn record_fee_broadcast(&self, record: &NewFeeRecord<'_>) -> Result<()> {
+        fee_history::insert(self.writer(), record, self.now().timestamp()).await
+    }
+
+    /// Completes a fee row from its receipt; `false` if there was no pending row.
+    pub async fn complete_fee_record(
+        &self,
+        network: &str,
+        tx_hash: &str,
+        gas_used: u64,
+        effective_gas_price: u64,
+    ) -> Result<bool> {
+        fee_history::complete(self.writer(), network, tx_hash, gas_used, effective_gas_price, self.now().timestamp())
+            .await
+    }
+
+    pub async fn fee_record(&self, network: &str, tx_hash: &str) -> Result<Option<FeeRecord>> {
+        fee_history::get(self.writer(), network, tx_hash).await
+    }
+
+    /// Fee rows created since `since` that still lack a receipt, oldest first.
+    pub async fn unconfirmed_fee_records(&self, since: DateTime<Utc>, limit: i64) -> Result<Vec<FeeRecord>> {
+        fee_history::unconfirmed(self.writer(), since.timestamp(), limit).await
+    }
+
+    /// [`Self::unconfirmed_fee_records`] of one network.
+    pub async fn unconfirmed_fee_records_on(
+        &self,
+        network: &str,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<FeeRecord>> {
+        fee_history::unconfirmed_on(self.writer(), network, since.timestamp(), limit).await
+    }
+
+    /// [`Self::unconfirmed_fee_records`] of every network but `skip`.
+    pub async fn unconfirmed_fee_records_except(
+        &self,
+        skip: &[String],
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<FeeRecord>> {
+        fee_history::unconfirmed_except(self.writer(), skip, since.timestamp(), limit).await
+    }
+
+    pub async fn record_gas_price_sample(&self, network: &str, gas_price: u64, at: DateTime<Utc>) -> Result<()> {
+        fee_history::insert_gas_sample(self.writer(), network, gas_price, at.timestamp()).await
+    }
+
+    /// `(unix seconds, wei)` gas oracle samples since `since`
+    pub async fn gas_price_samples(&self, network: &str, since: DateTime<Utc>) -> Result<Vec<(i64, u64)>> {
+        fee_history::gas_samples(self.reader(), network, since.timestamp()).await
+    }
+
+    /// Fee aggregates per bucket since `since`, plus the window total.
+    pub async fn fee_analytics(
+        &self,
+        network: &str,
+        since: DateTime<Utc>,
+        grouping: FeeGrouping,
+    ) -> Result<(Vec<FeeSummary>, FeeSummary)> {
+        self.timed(DbPool::Reader, "fee_history.summarize", QueryClass::Analytics, |mut conn| async move {
+            fee_history::summarize(&mut conn, network, since.timestamp(), grouping).await
+        })
+        .await
+    }
+}
+
+// Approval API
+impl WalletStorage {
+    /// Stores a new `pending` approval with its sealed signing key and
+    /// journals the request.
+    pub async fn create_approval(&self, record: &ApprovalRecord, key: &ApprovalKey) -> Result<()> {
+        let mut tx = self.writer().begin().await?;
+        approvals::insert(&mut tx, record).await?;
+        approvals::insert_key(&mut tx, &record.id, key).await?;
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::APPROVAL_REQUESTED,
+                entity_type: "approval",
+                entity_id: &record.id,
+                payload: serde_json::json!({
+                    "wallet_name": record.wallet_name,
+                    "network": record.network,
+                    "to": record.payload.to,
+                    "amount": record.amount,
+                    "requested_by": record.requested_by,
+                    "expires_at": record.expires_at,
+                }),
+            },
+            self.now().timestamp(),
+        )
+        .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store approval request: {}", e))?;
+        self.journal_committed(seq);
+        Ok(())
+    }
+
+    pub async fn approval(&self, id: &str) -> Result<Option<ApprovalRecord>> {
+        approvals::get(self.writer(), id).await
+    }
+
+    pub async fn list_approvals(&self, status: Option<&str>, limit: i64) -> Result<Vec<ApprovalRecord>> {
+        approvals::list(self.writer(), status, limit).await
+    }
+
+    /// Sealed signing key of a `pending` approval.
+    pub async fn approval_key(&self, id: &str) -> Result<Option<ApprovalKey>> {
+        approvals::get_key(self.writer(), id).await
+    }
+
+    /// Compare-and-set status change, journaled with the change; `false` if
+    /// `id` was not in `from` (or, leaving `pending`, had already expired).
+    /// Leaving `pending` deletes the sealed signing key in the same transaction.
+    pub async fn transition_approval(
+        &self,
+        id: &str,
+        from: &str,
+        to: &str,
+        decision: &ApprovalDecision<'_>,
+    ) -> Result<bool> {
+        let mut tx = self.writer().begin().await?;
+        if !approvals::transition(&mut tx, id, from, to, decision, self.now().timestamp()).await? {
+            return Ok(false);
+        }
+        if from == APPROVAL_PENDING {
+            approvals::delete_key(&mut tx, id).await?;
+        }
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::APPROVAL_STATUS_CHANGED,
+                entity_type: "approval",
+                entity_id: id,
+                payload: serde_json::json!({
+                    "from_status": from,
+                    "to_status": to,
+                    "decided_by": decision.decided_by,
+                    "reason": decision.reason,
+                }),
+            },
+            self.now().timestamp(),
+        )
+        .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to update approval: {}", e))?;
+        self.journal_committed(seq);
+        Ok(true)
+    }
+
+    pub async fn set_approval_tx_hash(&self, id: &str, tx_hash: &str) -> Result<()> {
+        approvals::set_tx_hash(self.writer(), id, tx_hash).await
+    }
+
+    /// Marks every overdue `pending` approval `expired`; returns their ids.
+    pub async fn expire_approvals(&self) -> Result<Vec<String>> {
+        let mut expired = Vec::new();
+        for id in approvals::due_for_expiry(self.writer(), self.now().timestamp()).await? {
+            if self.transition_approval(&id, APPROVAL_PENDING, APPROVAL_EXPIRED, &ApprovalDecision::default()).await? {
+                expired.push(id);
+            }
+        }
+        Ok(expired)
+    }
+
+    /// Decimal whole units; `None` means sends are never held for review.
+    pub async fn review_threshold(&self, wallet_name: &str) -> Result<Option<String>> {
+        approvals::get_threshold(self.writer(), wallet_name).await
+    }
+
+    pub async fn set_review_threshold(
+        &self,
+        wallet_name: &str,
+        threshold: Option<&str>,
+        updated_by: &str,
+    ) -> Result<()> {
+        let mut tx = self.writer().begin().await?;
+        approvals::put_threshold(&mut tx, wallet_name, threshold, updated_by, self.now().timestamp()).await?;
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::LIMITS_CHANGED,
+                entity_type: "wallet",
+                entity_id: wallet_name,
+                payload: serde_json::json!({ "review_threshold": threshold, "updated_by": updated_by }),
+            },
+            self.now().timestamp(),
+        )
+        .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store review threshold: {}", e))?;
+        self.journal_committed(seq);
+        Ok(())
+    }
+}
+
+// Spending limit API
+impl WalletStorage {
+    pub async fn spending_limit(&self, wallet_name: &str, network: &str) -> Result<Option<SpendingLimitRecord>> {
+        spending_limits::get(self.writer(), wallet_name, network).await
+    }
+
+    /// Every network the wallet has a limit on, by network.
+    pub async fn spending_limits(&self, wallet_name: &str) -> Result<Vec<SpendingLimitRecord>> {
+        spending_limits::for_wallet(self.writer(), wallet_name).await
+    }
+
+    /// Sets both limits of `wallet_name` on `network`; with neither limit the
+    /// row is removed. Returns the stored record, if any.
+    pub async fn set_spending_limit(
+        &self,
+        wallet_name: &str,
+        network: &str,
+        max_per_tx: Option<u128>,
+        max_per_day: Option<u128>,
+        updated_by: &str,
+    ) -> Result<Option<SpendingLimitRecord>> {
+        let record = SpendingLimitRecord {
+            wallet_name: wallet_name.to_string(),
+            network: network.to_string(),
+            max_per_tx,
+            max_per_day,
+            updated_by: updated_by.to_string(),
+            updated_at: self.now().timestamp(),
+        };
+        let mut tx = self.writer().begin().await?;
+        if max_per_tx.is_none() && max_per_day.is_none() {
+            spending_limits::delete(&mut tx, wallet_name, network).await?;
+        } else {
+            spending_limits::upsert(&mut tx, &record).await?;
+        }
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::LIMITS_CHANGED,
+                entity_type: "wallet",
+                entity_id: wallet_name,
+                payload: serde_json::json!({
+                    "network": network,
+                    "max_per_tx": max_per_tx.map(|v| v.to_string()),
+                    "max_per_day": max_per_day.map(|v| v.to_string()),
+                    "updated_by": updated_by,
+                }),
+            },
+            self.now().timestamp(),
+        )
+        .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store spending limit: {}", e))?;
+        self.journal_committed(seq);
+        Ok((max_per_tx.is_some() || max_per_day.is_some()).then_some(record))
+    }
+
+    /// `false` if the wallet had no limit on `network`.
+    pub async fn delete_spending_limit(&self, wallet_name: &str, network: &str, updated_by: &str) -> Result<bool> {
+        let existed = self.spending_limit(wallet_name, network).await?.is_some();
+        if existed {
+            self.set_spending_limit(wallet_name, network, None, None, updated_by).await?;
+        }
+        Ok(existed)
+    }
+
+    /// Held by a server-signed send from its spending limit check until the
+    /// send is recorded, so concurrent sends cannot overrun the daily cap together.
+    pub async fn lock_sends(&self, wallet_name: &str, network: &str) -> tokio::sync::OwnedMutexGuard<()> {
+        self.send_locks.lock(wallet_name, network).await
+    }
+
+    /// What the wallet sent on `network` in the last 24 hours (`pending` and
+    /// `confirmed`), in the smallest unit.
+    pub async fn sent_in_last_day(&self, wallet_name: &str, network: &str) -> Result<u128> {
+        let since = self.now() - chrono::Duration::hours(24);
+        spending_limits::sent_since(self.writer(), wallet_name, network, since).await
+    }
+}
+
+// Wallet group API
+impl WalletStorage {
+    /// `false` if a group named `name` already exists.
+    pub async fn create_wallet_group(&self, name: &str, owner_user_id: &str, created_by: &str) -> Result<bool> {
+        let record = WalletGroupRecord {
+            name: name.to_string(),
+            owner_user_id: owner_user_id.to_string(),
+            created_by: created_by.to_string(),
+            created_at: self.now().timestamp(),
+        };
+        let mut tx = self.writer().begin().await?;
+        let created = wallet_groups::insert(&mut tx, &record).await?;
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    pub async fn wallet_group(&self, name: &str) -> Result<Option<WalletGroupRecord>> {
+        wallet_groups::get(self.writer(), name).await
+    }
+
+    /// All groups, or only those of `owner_user_id`.
+    pub async fn list_wallet_groups(&self, owner_user_id: Option<&str>) -> Result<Vec<WalletGroupRecord>> {
+        wallet_groups::list(self.writer(), owner_user_id).await
+    }
+
+    /// Removes the group and its memberships; never touches the member wallets.
+    pub async fn delete_wallet_group(&self, name: &str) -> Result<bool> {
+        let mut tx = self.writer().begin().await?;
+        let deleted = wallet_groups::delete(&mut tx, name).await?;
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    /// `false` if the wallet already is a member.
+    pub async fn add_wallet_group_member(&self, group_name: &str, wallet_name: &str, added_by: &str) -> Result<bool> {
+        let mut tx = self.writer().begin().await?;
+        let added = wallet_groups::add_member(&mut tx, group_name, wallet_name, added_by, self.now().timestamp()).await?;
+        tx.commit().await?;
+        Ok(added)
+    }
+
+    pub async fn remove_wallet_group_member(&self, group_name: &str, wallet_name: &str) -> Result<bool> {
+        let mut tx = self.writer().begin().await?;
+        let removed = wallet_groups::remove_member(&mut tx, group_name, wallet_name).await?;
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    pub async fn wallet_group_members(&self, group_name: &str) -> Result<Vec<WalletGroupMember>> {
+        wallet_groups::members(self.writer(), group_name).await
+    }
+
+    /// Drops a wallet deleted outside this database (non-custodial bindings
+    /// live in users.db) from every group; returns how many it was in.
+    pub async fn remove_wallet_from_groups(&self, wallet_name: &str) -> Result<u64> {
+        let mut tx = self.writer().begin().await?;
+        let removed = wallet_groups::delete_memberships(&mut tx, wallet_name).await?;
+        tx.commit().await?;
+        Ok(removed)
+    }
+}
+
+// Balance subscription API
+impl WalletStorage {
+    pub async fn insert_balance_subscription(&self, sub: &NewBalanceSubscription<'_>) -> Result<()> {
+        balance_subscriptions::insert(self.writer(), sub, self.now().timestamp()).await
+    }
+
+    pub async fn balance_subscription(&self, wallet_id: i64, id: &str) -> Result<Option<BalanceSubscriptionRecord>> {
+        balance_subscriptions::get(self.writer(), wallet_id, id).await
+    }
+
+    pub async fn balance_subscriptions_for(&self, wallet_id: i64) -> Result<Vec<BalanceSubscriptionRecord>> {
+        balance_subscriptions::list_for_wallet(self.writer(), wallet_id).await
+    }
+
+    /// `false` if no such subscription exists on the wallet.
+    pub async fn update_balance_subscription(
+        &self,
+        wallet_id: i64,
+        id: &str,
+        active: Option<bool>,
+        min_delta: Option<u128>,
+    ) -> Result<bool> {
+        balance_subscriptions::update(self.writer(), wallet_id, id, active, min_delta, self.now().timestamp()).await
+    }
+
+    pub async fn delete_balance_subscription(&self, wallet_id: i64, id: &str) -> Result<bool> {
+        balance_subscriptions::delete(self.writer(), wallet_id, id).await
+    }
+
+    /// Called when the wallet binding is removed; returns how many were active.
+    pub async fn deactivate_balance_subscriptions(&self, wallet_id: i64) -> Result<u64> {
+        balance_subscriptions::deactivate_for_wallet(self.writer(), wallet_id, self.now().timestamp()).await
+    }
+
+    /// Evaluates every active subscription on the observed (wallet, network,
+    /// token) and journals a `balance.changed` event for each one whose
+    /// change has outlasted `debounce_secs`. State updates and events commit
+    /// together, so repeating an observation never reports twice. Returns
+    /// the number of events written.
+    pub async fn observe_balance(&self, obs: &BalanceObservation<'_>, debounce_secs: u64) -> Result<usize> {
+        let now = self.now().timestamp();
+        let mut tx = self.writer().begin().await?;
+        let subs = balance_subscriptions::active_for(&mut tx, obs.wallet_id, obs.network, obs.token).await?;
+        if subs.is_empty() {
+            return Ok(0);
+        }
+        let mut seqs = Vec::new();
+        for sub in subs {
+            let step = sub.evaluate(obs.balance, debounce_secs, now)?;
+            if let SubscriptionStep::Notify { old, first_change_at } = step {
+                let tx_hash = obs.tx_hash.or(sub.pending_tx_hash.as_deref());
+                let seq = events_journal::append(
+                    &mut tx,
+                    &NewJournalEvent {
+                        event_type: events_journal::BALANCE_CHANGED,
+                        entity_type: "balance_subscription",
+                        entity_id: &sub.id,
+                        payload: serde_json::json!({
+                            "subscription_id": sub.id,
+                            "webhook_target": sub.webhook_target,
+                            "wallet_name": sub.wallet_name,
+                            "network": sub.network,
+                            "token": sub.token,
+                            "old_balance": old.to_string(),
+                            "new_balance": obs.balance.to_string(),
+                            "delta": balance_subscriptions::signed_delta(old, obs.balance),
+                            "tx_hash": tx_hash,
+                            "first_change_at": first_change_at,
+                        }),
+                    },
+                    now,
+                )
+                .await?;
+                seqs.push(seq);
+            }
+            balance_subscriptions::apply(&mut tx, &sub.id, step, obs.balance, obs.tx_hash, now).await?;
+        }
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to evaluate balance subscriptions: {}", e))?;
+        for seq in &seqs {
+            self.journal_committed(*seq);
+        }
+        Ok(seqs.len())
+    }
+}
+
+// Wallet behavioral profiles
+impl WalletStorage {
+    /// Rolling send profile of `wallet_id` on `network`; `None` before its
+    /// first confirmed send.
+    pub async fn wallet_profile(&self, wallet_id: &str, network: &str) -> Result<Option<crate::anomaly_detection::WalletProfile>> {
+        let mut conn = self.writer().acquire().await?;
+        wallet_profiles::get(&mut conn, wallet_id, network).await
+    }
+
+    /// Whether a confirmed send from `wallet_id` on `network` ever paid `recipient`
+    pub async fn is_known_recipient(&self, wallet_id: &str, network: &str, recipient: &str) -> Result<bool> {
+        wallet_profiles::is_known_recipient(self.writer(), wallet_id, network, recipient).await
+    }
+
+    /// Drops and recomputes the profiles of `wallet_id` (every wallet when
+    /// `None`) from its confirmed transactions.
+    pub async fn rebuild_wallet_profiles(&self, wallet_id: Option<&str>) -> Result<ProfileRebuildReport> {
+        wallet_profiles::rebuild(self.writer(), wallet_id, self.now().timestamp()).await
+    }
+}
+
+// Dead-man's switch
+impl WalletStorage {
+    /// Arms (or re-arms) the wallet's switch; the timer starts now.
+    pub async fn put_deadman_policy(&self, policy: &NewDeadmanPolicy<'_>) -> Result<DeadmanPolicy> {
+        let mut tx = self.writer().begin().await?;
+        deadman_switches::upsert(&mut tx, policy, self.now().timestamp()).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store dead-man policy: {}", e))?;
+        self.deadman_policy(policy.wallet_name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Dead-man policy of {} vanished", policy.wallet_name))
+    }
+
+    pub async fn deadman_policy(&self, wallet_name: &str) -> Result<Option<DeadmanPolicy>> {
+        deadman_switches::get(self.writer(), wallet_name).await
+    }
+
+    /// Armed policies and triggered ones with networks still to sweep
+    pub async fn active_deadman_policies(&self) -> Result<Vec<DeadmanPolicy>> {
+        deadman_switches::list_active(self.writer()).await
+    }
+
+    /// Restarts the wallet's timer; false when no armed policy exists.
+    pub async fn deadman_checkin(&self, wallet_name: &str) -> Result<bool> {
+        deadman_switches::checkin(self.writer(), wallet_name, self.now().timestamp()).await
+    }
+
+    pub async fn delete_deadman_policy(&self, wallet_name: &str) -> Result<bool> {
+        let mut tx = self.writer().begin().await?;
+        let deleted = deadman_switches::delete(&mut tx, wallet_name).await?;
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    /// Journals a `deadman.warning` and marks the first `warnings_due`
+    /// warnings of the schedule as sent. False (nothing journaled) when the
+    /// policy was checked in or already warned since it was read.
+    pub async fn record_deadman_warning(&self, policy: &DeadmanPolicy, warnings_due: usize) -> Result<bool> {
+        let now = self.now().timestamp();
+        let mut tx = self.writer().begin().await?;
+        if !deadman_switches::record_warning(&mut tx, policy, warnings_due).await? {
+            return Ok(false);
+        }
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::DEADMAN_SWITCH_WARNING,
+                entity_type: "deadman_policy",
+                entity_id: &policy.wallet_name,
+                payload: serde_json::json!({
+                    "wallet_name": policy.wallet_name,
+                    "beneficiary": policy.beneficiary,
+                    "networks": policy.networks,
+                    "deadline": policy.deadline(),
+                    "seconds_remaining": (policy.deadline() - now).max(0),
+                    "warning": warnings_due,
+                    "warnings_scheduled": policy.warning_secs.len(),
+                }),
+            },
+            now,
+        )
+        .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to record dead-man warning: {}", e))?;
+        self.journal_committed(seq);
+        Ok(true)
+    }
+
+    /// Flips an armed policy whose deadline passed to triggered and journals
+    /// `deadman.triggered`. Exactly one caller wins per period.
+    pub async fn trigger_deadman(&self, policy: &DeadmanPolicy) -> Result<bool> {
+        let now = self.now().timestamp();
+        let mut tx = self.writer().begin().await?;
+        if !deadman_switches::trigger(&mut tx, policy, now).await? {
+            return Ok(false);
+        }
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::DEADMAN_SWITCH_TRIGGERED,
+                entity_type: "deadman_policy",
+                entity_id: &policy.wallet_name,
+                payload: serde_json::json!({
+                    "wallet_name": policy.wallet_name,
+                    "beneficiary": policy.beneficiary,
+                    "networks": policy.networks,
+                    "last_checkin_at": policy.last_checkin_at,
+                    "deadline": policy.deadline(),
+                }),
+            },
+            now,
+        )
+        .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to trigger dead-man policy: {}", e))?;
+        self.journal_committed(seq);
+        Ok(true)
+    }
+
+    /// Records which networks of a triggered policy still have to be swept
+    pub async fn set_deadman_pending(&self, wallet_name: &str, pending: &[String]) -> Result<()> {
+        deadman_switches::set_pending(self.writer(), wallet_name, pending).await
+    }
+}
+
+// Address-control attestations
+impl WalletStorage {
+    pub async fn record_attestation(&self, attestation: &NewAttestation<'_>) -> Result<AttestationRecord> {
+        attestations::insert(self.writer(), &self.ids.new_id(), attestation, self.now().timestamp()).await
+    }
+
+    /// The archived attestation carrying `signature`, revoked or not
+    pub async fn attestation_by_signature(&self, signature: &str) -> Result<Option<AttestationRecord>> {
+        attestations::find_by_signature(self.writer(), signature).await
+    }
+
+    pub async fn attestations_for(&self, wallet_name: &str) -> Result<Vec<AttestationRecord>> {
+        attestations::list_for_wallet(self.writer(), wallet_name).await
+    }
+
+    /// `false` if the wallet has no such unrevoked attestation.
+    pub async fn revoke_attestation(&self, wallet_name: &str, id: &str, reason: Option<&str>) -> Result<bool> {
+        attestations::revoke(self.writer(), wallet_name, id, reason, self.now().timestamp()).await
+    }
+}
+
+// Proof-of-reserves reports
+impl WalletStorage {
+    /// Stores a signed report and returns its id.
+    pub async fn record_reserve_report(&self, report: &NewReserveReport<'_>) -> Result<String> {
+        let id = self.ids.new_id();
+        reserve_reports::insert(self.writer(), &id, report, self.now().timestamp()).await?;
+        Ok(id)
+    }
+
+    pub async fn reserve_report(&self, id: &str) -> Result<Option<ReserveReportRecord>> {
+        reserve_reports::get(self.writer(), id).await
+    }
+
+    /// Newest first, with the total count
+    pub async fn reserve_reports(&self, offset: usize, limit: usize) -> Result<(Vec<ReserveReportSummary>, usize)> {
+        reserve_reports::list(self.reader(), offset, limit).await
+    }
+}
+
+// Operation bundles
+impl WalletStorage {
+    /// Stores a bundle with all steps `pending` and returns its id.
+    pub async fn create_bundle(&self, bundle: &NewBundle<'_>) -> Result<String> {
+        let id = self.ids.new_id();
+        let mut tx = self.writer().begin().await?;
+        operation_bundles::insert(&mut tx, &id, bundle, self.now().timestamp()).await?;
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::BUNDLE_CREATED,
+                entity_type: "operation_bundle",
+                entity_id: &id,
+                payload: serde_json::json!({
+                    "wallet_name": bundle.wallet_name,
+                    "failure_policy": bundle.failure_policy,
+                    "requested_by": bundle.requested_by,
+                    "steps": bundle.steps.iter().map(|s| s.step_id).collect::<Vec<_>>(),
+                }),
+            },
+            self.now().timestamp(),
+        )
+        .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store bundle: {}", e))?;
+        self.journal_committed(seq);
+        Ok(id)
+    }
+
+    pub async fn bundle(&self, id: &str) -> Result<Option<BundleRecord>> {
+        operation_bundles::get(self.writer(), id).await
+    }
+
+    /// Compare-and-set status change, journaled with the change.
+    pub async fn transition_bundle(&self, id: &str, from: &str, to: &str) -> Result<bool> {
+        let mut tx = self.writer().begin().await?;
+        if !operation_bundles::transition(&mut tx, id, from, to, self.now().timestamp()).await? {
+            return Ok(false);
+        }
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::BUNDLE_STATUS_CHANGED,
+                entity_type: "operation_bundle",
+                entity_id: id,
+                payload: serde_json::json!({ "from_status": from, "to_status": to }),
+            },
+            self.now().timestamp(),
+        )
+        .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to update bundle: {}", e))?;
+        self.journal_committed(seq);
+        Ok(true)
+    }
+
+    /// Compare-and-set on one step's status; the journal event carries the
+    /// step id and any error or transaction hash.
+    pub async fn transition_bundle_step(
+        &self,
+        bundle_id: &str,
+        step: &BundleStepRecord,
+        to: &str,
+        progress: &StepProgress<'_>,
+    ) -> Result<bool> {
+        let now = self.now().timestamp();
+        let mut tx = self.writer().begin().await?;
+        if !operation_bundles::transition_step(&mut tx, bundle_id, step.position, &step.status, to, progress, now)
+            .await?
+        {
+            return Ok(false);
+        }
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::BUNDLE_STEP_STATUS_CHANGED,
+                entity_type: "operation_bundle",
+                entity_id: bundle_id,
+                payload: serde_json::json!({
+                    "step_id": step.step_id,
+                    "kind": step.kind,
+                    "from_status": step.status,
+                    "to_status": to,
+                    "error_code": progress.error_code,
+                    "tx_hash": progress.output.and_then(|o| o.get("tx_hash")),
+                }),
+            },
+            now,
+        )
+        .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to update bundle step: {}", e))?;
+        self.journal_committed(seq);
+        Ok(true)
+    }
+
+    /// Marks every `running` bundle `interrupted`: after a restart nothing is
+    /// executing them any more. Returns their ids.
+    pub async fn interrupt_running_bundles(&self) -> Result<Vec<String>> {
+        let mut interrupted = Vec::new();
+        for id in operation_bundles::ids_with_status(self.writer(), BUNDLE_RUNNING).await? {
+            if self.transition_bundle(&id, BUNDLE_RUNNING, BUNDLE_INTERRUPTED).await? {
+                interrupted.push(id);
+            }
+        }
+        Ok(interrupted)
+    }
+}
+
+// Address book
+impl WalletStorage {
+    pub async fn address_book(&self, wallet_name: &str) -> Result<Vec<AddressBookEntry>> {
+        address_book::list(self.writer(), wallet_name).await
+    }
+
+    /// Writes all of `entries` or none of them
+    pub async fn upsert_address_book(&self, wallet_name: &str, entries: &[AddressBookEntry]) -> Result<()> {
+        let mut tx = self.writer().begin().await?;
+        address_book::upsert(&mut tx, wallet_name, entries, self.now().timestamp()).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to write address book: {}", e))?;
+        Ok(())
+    }
+}
+
+// Database backup API
+impl WalletStorage {
+    /// Writes a transactionally consistent copy of the database to `dest`
+    /// (`VACUUM INTO`); `dest` must not exist yet.
+    pub async fn snapshot_into(&self, dest: &std::path::Path) -> Result<()> {
+        let dest = dest.to_str().ok_or_else(|| anyhow::anyhow!("Backup path is not valid UTF-8"))?;
+        sqlx::query("VACUUM INTO ?1")
+            .bind(dest)
+            .execute(self.writer())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to snapshot database: {}", e))?;
+        Ok(())
+    }
+
+    pub async fn record_backup(&self, record: &NewBackupRecord<'_>) -> Result<i64> {
+        backup_history::insert(self.writer(), record).await
+    }
+
+    pub async fn backup_history(&self, limit: i64) -> Result<Vec<BackupRecord>> {
+        backup_history::list(self.reader(), limit).await
+    }
+
+    /// Backups whose stored object still exists, most recent first.
+    pub async fn unpruned_backups(&self) -> Result<Vec<BackupRecord>> {
+        backup_history::unpruned(self.writer()).await
+    }
+
+    pub async fn mark_backup_pruned(&self, id: i64, pruned_at: DateTime<Utc>) -> Result<()> {
+        backup_history::mark_pruned(self.writer(), id, pruned_at.timestamp()).await
+    }
+}
+
+// Process incidents
+impl WalletStorage {
+    pub async fn record_incident(&self, incident: &NewIncident<'_>) -> Result<i64> {
+        process_incidents::insert(self.writer(), incident).await
+    }
+
+    /// Incidents at or after `since`, most recent first, and how many there
+    /// were in total.
+    pub async fn recent_incidents(&self, since: DateTime<Utc>, limit: i64) -> Result<(Vec<IncidentRecord>, i64)> {
+        let since = since.timestamp();
+        let incidents = process_incidents::since(self.reader(), since, limit).await?;
+        let total = process_incidents::count_since(self.reader(), since).await?;
+        Ok((incidents, total))
+    }
+}
+
+// History backfill
+impl WalletStorage {
+    /// Starts or extends a backfill; see [`NewScanRequest`].
+    pub async fn request_backfill(&self, req: &NewScanRequest<'_>) -> Result<ScanCursor> {
+        let mut tx = self.writer().begin().await?;
+        let cursor = history_backfill::request(&mut tx, req, self.now().timestamp()).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store scan cursor: {}", e))?;
+        Ok(cursor)
+    }
+
+    /// Backfills with blocks left to read, oldest request first
+    pub async fn running_backfills(&self) -> Result<Vec<ScanCursor>> {
+        history_backfill::running(self.writer()).await
+    }
+
+    pub async fn backfill_cursors(&self, wallet_name: &str) -> Result<Vec<ScanCursor>> {
+        history_backfill::for_wallet(self.writer(), wallet_name).await
+    }
+
+    /// Stores the entries read from `cursor.next_block..=through_block` and
+    /// advances the cursor past them, in one transaction.
+    ///
+    /// Entries already stored are skipped. Each transaction hash that gained
+    /// entries also gets a `transactions` row unless the wallet already has
+    /// one for it (a send made through this service) or is not stored (a
+    /// non-custodial wallet); native sends that succeeded count toward the
+    /// wallet's profile. Returns how many entries were new.
+    pub async fn store_backfill_batch(
+        &self,
+        cursor: &ScanCursor,
+        entries: &[NewLedgerEntry],
+        through_block: u64,
+    ) -> Result<usize> {
+        let now = self.now().timestamp();
+        let mut tx = self.writer().begin().await?;
+        let (inserted, last_seq) = self.record_ledger_entries(&mut tx, &cursor.scope(), entries).await?;
+        history_backfill::advance(&mut tx, cursor, through_block as i64, inserted as i64, now).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store backfill batch: {}", e))?;
+        if let Some(seq) = last_seq {
+            self.journal_committed(seq);
+        }
+        Ok(inserted)
+    }
+
+    /// Records on-chain transfers of `scope` found outside a backfill (by
+    /// reconciliation), exactly as a backfill batch would but without moving
+    /// any cursor. Returns how many entries were new.
+    pub async fn record_onchain_transfers(&self, scope: &LedgerScope<'_>, entries: &[NewLedgerEntry]) -> Result<usize> {
+        let mut tx = self.writer().begin().await?;
+        let (inserted, last_seq) = self.record_ledger_entries(&mut tx, scope, entries).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to record on-chain transfers: {}", e))?;
+        if let Some(seq) = last_seq {
+            self.journal_committed(seq);
+        }
+        Ok(inserted)
+    }
+
+    /// Inserts the new ones of `entries` and, for a stored wallet, a
+    /// `transactions` row for each hash it has no record of; returns the number inserted and the
+    /// journal sequence of the last row created.
+    async fn record_ledger_entries(
+        &self,
+        conn: &mut sqlx::SqliteConnection,
+        scope: &LedgerScope<'_>,
+        entries: &[NewLedgerEntry],
+    ) -> Result<(usize, Option<i64>)> {
+        let inserted = history_backfill::insert_entries(conn, scope, entries, self.now().timestamp()).await?;
+
+        let mut by_hash: Vec<(&str, Vec<&NewLedgerEntry>)> = Vec::new();
+        for entry in &inserted {
+            match by_hash.iter_mut().find(|(hash, _)| *hash == entry.tx_hash) {
+                Some((_, group)) => group.push(entry),
+                None => by_hash.push((&entry.tx_hash, vec![entry])),
+            }
+        }
+        if by_hash.is_empty() {
+            return Ok((0, None));
+        }
+        // non-custodial wallets have no `wallets` row to key records by; the ledger is their history
+        let Some(wallet_id): Option<String> = sqlx::query_scalar("SELECT id FROM wallets WHERE name = ?1")
+            .bind(scope.wallet_name)
+            .fetch_optional(&mut *conn)
+            .await?
+        else {
+            return Ok((inserted.len(), None));
+        };
+        let mut last_seq = None;
+        for (tx_hash, group) in by_hash {
+            let known: Option<i64> =
+                sqlx::query_scalar("SELECT 1 FROM transactions WHERE wallet_id = ?1 AND tx_hash = ?2")
+                    .bind(&wallet_id)
+                    .bind(tx_hash)
+                    .fetch_optional(&mut *conn)
+                    .await?;
+            if known.is_some() {
+                continue;
+            }
+            let native = group.iter().find(|e| e.log_index == history_backfill::TX_ENTRY_INDEX);
+            let lead = native.unwrap_or(&group[0]);
+            let counterparty = lead.counterparty.clone().unwrap_or_default();
+            let (from_address, to_address) = if lead.direction == DIRECTION_IN {
+                (counterparty, scope.address.to_string())
+            } else {
+                (scope.address.to_string(), counterparty)
+            };
+            let success = native.is_none_or(|e| e.success);
+            let moved = native.filter(|e| e.success).map_or(ethers::types::U256::zero(), |e| e.amount);
+            let occurred_at = DateTime::from_timestamp(lead.occurred_at, 0).unwrap_or_else(|| self.now());
+            let record = TransactionRecord {
+                id: self.ids.new_id(),
+                wallet_id: wallet_id.clone(),
+                tx_hash: tx_hash.to_string(),
+                network: scope.network.to_string(),
+                from_address,
+                to_address,
+                amount: ethers::utils::format_ether(moved),
+                fee: ethers::utils::format_ether(native.map_or(ethers::types::U256::zero(), |e| e.fee)),
+                status: if success { "confirmed" } else { "failed" }.to_string(),
+                created_at: occurred_at,
+                confirmed_at: Some(occurred_at),
+                integrity_hash: String::new(),
+                fee_actual: None,
+                block_number: None,
+            };
+            let profile = lead.direction == DIRECTION_OUT && !moved.is_zero();
+            last_seq = Some(self.insert_transaction_with(conn, &record, profile).await?);
+        }
+        Ok((inserted.len(), last_seq))
+    }
+
+    /// Keeps the error on the cursor; the next run retries the same blocks.
+    pub async fn record_backfill_error(&self, cursor: &ScanCursor, error: &str) -> Result<()> {
+        history_backfill::record_error(self.writer(), cursor, error, self.now().timestamp()).await
+    }
+
+    pub async fn ledger_entries(&self, wallet_name: &str, network: &str) -> Result<Vec<LedgerEntryRecord>> {
+        history_backfill::entries(self.reader(), wallet_name, network).await
+    }
+
+    /// Per-token totals of the backfilled ledger of `wallet_name` on `network`
+    pub async fn ledger_balances(&self, wallet_name: &str, network: &str) -> Result<Vec<LedgerBalance>> {
+        history_backfill::balances(&self.ledger_entries(wallet_name, network).await?)
+    }
+}
+
+// On-chain reconciliation
+impl WalletStorage {
+    /// Queues a reconciliation run for the job to pick up.
+    pub async fn queue_reconciliation(&self, run: &NewReconciliationRun<'_>) -> Result<ReconciliationRun> {
+        let id = self.ids.new_id();
+        reconciliation_runs::insert(self.writer(), &id, run, self.now().timestamp()).await?;
+        reconciliation_runs::get(self.writer(), &id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Reconciliation run {} vanished after insert", id))
+    }
+
+    pub async fn reconciliation_run(&self, id: &str) -> Result<Option<ReconciliationRun>> {
+        reconciliation_runs::get(self.writer(), id).await
+    }
+
+    /// Claims the oldest queued run; `None` when nothing is queued.
+    pub async fn claim_reconciliation(&self) -> Result<Option<ReconciliationRun>> {
+        reconciliation_runs::claim_next(self.writer(), self.now().timestamp()).await
+    }
+
+    /// Stores the report of a running run and completes it; `false` if the
+    /// run was not running (failed meanwhile).
+    pub async fn complete_reconciliation(
+        &self,
+        id: &str,
+        totals: &ReconciliationTotals,
+        discrepancies: &[Discrepancy],
+    ) -> Result<bool> {
+        let mut tx = self.writer().begin().await?;
+        let completed =
+            reconciliation_runs::complete(&mut tx, id, totals, discrepancies, self.now().timestamp()).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store reconciliation report: {}", e))?;
+        Ok(completed)
+    }
+
+    pub async fn fail_reconciliation(&self, id: &str, error: &str) -> Result<()> {
+        reconciliation_runs::fail(self.writer(), id, error, self.now().timestamp()).await
+    }
+
+    /// Findings of a run after position `after`, in report order
+    pub async fn reconciliation_discrepancies(
+        &self,
+        run_id: &str,
+        after: Option<i64>,
+        limit: usize,
+    ) -> Result<Vec<StoredDiscrepancy>> {
+        reconciliation_runs::discrepancies(self.reader(), run_id, after, limit).await
+    }
+
+    /// The record the wallet named `wallet_name` keeps of `tx_hash`, if any
+    pub async fn transaction_for_wallet(&self, wallet_name: &str, tx_hash: &str) -> Result<Option<TransactionRecord>> {
+        let tx = sqlx::query_as::<_, TransactionRecord>(
+            r#"
+            SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at,
+                   confirmed_at, integrity_hash, fee_actual, block_number
+            FROM transactions
+            WHERE wallet_id = (SELECT id FROM wallets WHERE name = ?1) AND tx_hash = ?2 COLLATE NOCASE
+            "#,
+        )
+        .bind(wallet_name)
+        .bind(tx_hash)
+        .fetch_optional(self.writer())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load transaction: {}", e))?;
+        if let Some(tx) = &tx {
+            Self::verify_transaction_integrity(tx)?;
+        }
+        Ok(tx)
+    }
+
+    /// Fills in the fee of a transaction recorded without one (empty or
+    /// zero), re-sealing its integrity hash. A fee already recorded is never
+    /// overwritten; returns whether the fee was filled.
+    pub async fn fill_transaction_fee(&self, id: &str, fee: &str) -> Result<bool> {
+        let mut tx = sqlx::query_as::<_, TransactionRecord>(
+            r#"
+            SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at,
+                   confirmed_at, integrity_hash, fee_actual, block_number
+            FROM transactions WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.writer())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load transaction: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("Transaction not found: {}", id))?;
+        Self::verify_transaction_integrity(&tx)?;
+        // "", "0" and "0.0" all mean the fee was never known
+        if !tx.fee.trim().trim_start_matches(['0', '.']).is_empty() {
+            return Ok(false);
+        }
+        let previous_fee = std::mem::replace(&mut tx.fee, fee.to_string());
+        let integrity_hash = Self::calculate_transaction_integrity_hash(&tx);
+        let updated = sqlx::query("UPDATE transactions SET fee = ?1, integrity_hash = ?2 WHERE id = ?3 AND fee = ?4")
+            .bind(&tx.fee)
+            .bind(integrity_hash)
+            .bind(id)
+            .bind(previous_fee)
+            .execute(self.writer())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to fill transaction fee: {}", e))?;
+        Ok(updated.rows_affected() == 1)
+    }
+
+    /// Records what the receipt of `tx_hash` says on every transaction row
+    /// with that hash: the fee actually paid, when it was confirmed and the
+    /// block it was mined in. A reverted send passes no `confirmed_at` and
+    /// keeps the one it had. Each row is integrity-checked and re-sealed;
+    /// returns how many rows were updated.
+    pub async fn finalize_transaction(
+        &self,
+        tx_hash: &str,
+        fee_actual: &str,
+        confirmed_at: Option<DateTime<Utc>>,
+        block_number: u64,
+    ) -> Result<u64> {
+        let block_number = i64::try_from(block_number).map_err(|_| anyhow::anyhow!("Block number out of range"))?;
+        let mut db_tx = self.writer().begin().await?;
+        let rows = sqlx::query_as::<_, TransactionRecord>(
+            r#"
+            SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at,
+                   confirmed_at, integrity_hash, fee_actual, block_number
+            FROM transactions WHERE tx_hash = ?1 COLLATE NOCASE
+            "#,
+        )
+        .bind(tx_hash)
+        .fetch_all(&mut *db_tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load transaction: {}", e))?;
+
+        let mut updated = 0;
+        for mut tx in rows {
+            Self::verify_transaction_integrity(&tx)?;
+            tx.fee_actual = Some(fee_actual.to_string());
+            tx.confirmed_at = confirmed_at.or(tx.confirmed_at);
+            tx.block_number = Some(block_number);
+            let integrity_hash = Self::calculate_transaction_integrity_hash(&tx);
+            updated += sqlx::query(
+                "UPDATE transactions SET fee_actual = ?1, confirmed_at = ?2, block_number = ?3, integrity_hash = ?4 \
+                 WHERE id = ?5",
+            )
+            .bind(&tx.fee_actual)
+            .bind(tx.confirmed_at)
+            .bind(tx.block_number)
+            .bind(integrity_hash)
+            .bind(&tx.id)
+            .execute(&mut *db_tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to finalize transaction: {}", e))?
+            .rows_affected();
+        }
+        db_tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to finalize transaction: {}", e))?;
+        Ok(updated)
+    }
+
+    /// Backfilled ledger entries of `tx_hash` on `network`, of every wallet
+    pub async fn ledger_entries_for_transaction(&self, network: &str, tx_hash: &str) -> Result<Vec<LedgerEntryRecord>> {
+        history_backfill::entries_for_transaction(self.reader(), network, &tx_hash.to_lowercase()).await
+    }
+
+    /// Wallets with an address on `network`
+    pub async fn network_wallets(&self, network: &str) -> Result<Vec<WalletNetworkRecord>> {
+        wallet_networks::on_network(self.writer(), network).await
+    }
+}
+
+type SchemaInitLock = Arc<tokio::sync::Mutex<()>>;
+
+/// Lock serializing schema initialization of `db_url` within this process
+fn schema_init_lock(db_url: &str) -> SchemaInitLock {
+    static LOCKS: std::sync::OnceLock<parking_lot::Mutex<HashMap<String, SchemaInitLock>>> =
+        std::sync::OnceLock::new();
+    LOCKS.get_or_init(Default::default).lock().entry(db_url.to_string()).or_default().clone()
+}
+
+/// How long startup waits for another process to finish initializing the schema
+const SCHEMA_INIT_LOCK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Exclusive lock on `<database>-init.lock` serializing schema initialization
+/// across processes. SQLite takes byte-range locks on the database itself, so
+/// the lock is on a file of its own; the holder removes it before unlocking.
+struct SchemaInitFileLock {
+    path: std::path::PathBuf,
+    _file: std::fs::File,
+}
+
+impl Drop for SchemaInitFileLock {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            debug!("could not remove {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Waits up to [`SCHEMA_INIT_LOCK_TIMEOUT`] for the lock file; `None` in memory.
+async fn schema_init_file_lock(db_url: &str) -> Result<Option<SchemaInitFileLock>> {
+    let Some(path) = sandbox::database_path(db_url)? else {
+        return Ok(None);
+    };
+    let mut lock_path = path.into_os_string();
+    lock_path.push("-init.lock");
+    let lock_path = std::path::PathBuf::from(lock_path);
+    let deadline = tokio::time::Instant::now() + SCHEMA_INIT_LOCK_TIMEOUT;
+    loop {
+        let file = std::fs::OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path)?;
+        match file.try_lock() {
+            // a lock taken on a file the previous holder has just removed guards nothing
+            Ok(()) if is_same_file(&file, &lock_path) => {
+                return Ok(Some(SchemaInitFileLock { path: lock_path, _file: file }));
+            }
+            Ok(()) | Err(std::fs::TryLockError::WouldBlock) => {}
+            Err(std::fs::TryLockError::Error(e)) => return Err(e.into()),
+        }
+        if tokio::time::Instant::now() >= deadline {
+            anyhow::bail!(
+                "another process has been initializing the schema for over {}s; remove {} if it is gone",
+                SCHEMA_INIT_LOCK_TIMEOUT.as_secs(),
+                lock_path.display()
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[cfg(unix)]
+fn is_same_file(file: &std::fs::File, path: &std::path::Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (file.metadata(), std::fs::metadata(path)) {
+        (Ok(open), Ok(named)) => open.dev() == named.dev() && open.ino() == named.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_file(_file: &std::fs::File, path: &std::path::Path) -> bool {
+    path.exists()
+}
+
+/// SQLITE_BUSY or SQLITE_LOCKED somewhere in `e`, also when the sqlx error
+/// was formatted into a message on the way up
+fn is_lock_contention(e: &anyhow::Error) -> bool {
+    e.chain().any(|cause| match cause.downcast_ref::<sqlx::Error>() {
+        Some(sqlx::Error::Database(db)) => {
+            db.code().and_then(|code| code.parse::<i32>().ok()).is_some_and(|code| matches!(code & 0xff, 5 | 6))
+        }
+        _ => {
+            let message = cause.to_string();
+            message.contains("database is locked") || message.contains("database table is locked")
+        }
+    })
+}
+
+// Feature flags
+impl WalletStorage {
+    /// Every stored rule. Read from the writer, like the epoch that says when
+    /// to read them again.
+    pub async fn feature_flags(&self) -> Result<Vec<FeatureFlagRecord>> {
+        feature_flags::list(self.writer()).await
+    }
+
+    /// Stores `rule`, replacing the previous rule of the same scope.
+    pub async fn set_feature_flag(&self, rule: &NewFeatureFlag<'_>) -> Result<FeatureFlagRecord> {
+        let now = self.now().timestamp();
+        let mut tx = self.writer().begin().await?;
+        let record = feature_flags::put(&mut tx, rule, now).await?;
+        cache_epochs::bump(&mut tx, cache_epochs::FEATURE_FLAGS, now).await?;
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::FEATURE_FLAG_CHANGED,
+                entity_type: "feature_flag",
+                entity_id: rule.flag,
+                payload: serde_json::json!({
+                    "wallet_id": rule.wallet_id,
+                    "enabled": rule.enabled,
+                    "rollout_percent": rule.rollout_percent,
+                    "updated_by": rule.updated_by,
+                }),
+            },
+            now,
+        )
+        .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store feature flag: {}", e))?;
+        self.journal_committed(seq);
+        Ok(record)
+    }
+
+    /// Drops the rule of `flag` for `wallet_id` (or the global one) so the
+    /// next broader rule applies; `false` if there was none.
+    pub async fn clear_feature_flag(&self, flag: &str, wallet_id: Option<&str>, updated_by: &str) -> Result<bool> {
+        let now = self.now().timestamp();
+        let mut tx = self.writer().begin().await?;
+        if !feature_flags::remove(&mut tx, flag, wallet_id).await? {
+            return Ok(false);
+        }
+        cache_epochs::bump(&mut tx, cache_epochs::FEATURE_FLAGS, now).await?;
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::FEATURE_FLAG_CHANGED,
+                entity_type: "feature_flag",
+                entity_id: flag,
+                payload: serde_json::json!({ "wallet_id": wallet_id, "cleared": true, "updated_by": updated_by }),
+            },
+            now,
+        )
+        .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to clear feature flag: {}", e))?;
+        self.journal_committed(seq);
+        Ok(true)
+    }
+}
+
+// ERC-20 tokens
+impl WalletStorage {
+    /// Token behavior overrides set through the admin API
+    pub async fn token_behaviors(&self) -> Result<Vec<TokenBehaviorRecord>> {
+        erc20_tokens::list_behaviors(self.reader()).await
+    }
+
+    /// The override of `token` on `network`. Read from the writer so a send
+    /// right after an admin change sees it.
+    pub async fn token_behavior(&self, network: &str, token: &str) -> Result<Option<TokenBehaviorRecord>> {
+        erc20_tokens::get_behavior(self.writer(), network, token).await
+    }
+
+    pub async fn set_token_behavior(
+        &self,
+        network: &str,
+        token: &str,
+        behavior: &TokenBehavior,
+        updated_by: &str,
+    ) -> Result<TokenBehaviorRecord> {
+        erc20_tokens::put_behavior(self.writer(), network, token, behavior, updated_by, self.now().timestamp()).await
+    }
+
+    /// Drops the override of `token` so the configured behavior applies
+    /// again; `false` if there was none.
+    pub async fn clear_token_behavior(&self, network: &str, token: &str) -> Result<bool> {
+        erc20_tokens::remove_behavior(self.writer(), network, token).await
+    }
+
+    /// Registers a token transfer whose receipt is checked for delivery.
+    pub async fn register_token_delivery(&self, delivery: &NewTokenDelivery<'_>) -> Result<()> {
+        erc20_tokens::insert_delivery(self.writer(), delivery, self.now().timestamp()).await
+    }
+
+    pub async fn token_delivery(&self, network: &str, tx_hash: &str) -> Result<Option<TokenDeliveryRecord>> {
+        erc20_tokens::delivery(self.writer(), network, tx_hash).await
+    }
+
+    /// Settles a pending delivery; `false` if it was not pending.
+    pub async fn settle_token_delivery(
+        &self,
+        network: &str,
+        tx_hash: &str,
+        delivered: &str,
+        status: &str,
+    ) -> Result<bool> {
+        erc20_tokens::settle(self.writer(), network, tx_hash, delivered, status, self.now().timestamp()).await
+    }
+}
+
+// Nonce lanes and time locks
+impl WalletStorage {
+    /// Takes the next nonce of `lane` for `address`, opening the lane at
+    /// `[start, start + size)` if it is not open.
+    pub async fn reserve_lane_nonce(
+        &self,
+        network: &str,
+        address: &str,
+        lane: &str,
+        start: u64,
+        size: u64,
+    ) -> Result<LaneReservation> {
+        let now = self.now().timestamp();
+        self.timed(DbPool::Writer, "nonce_lanes.reserve", QueryClass::Hot, |mut conn| async move {
+            nonce_lanes::reserve(&mut conn, network, address, lane, start, size, now).await
+        })
+        .await
+    }
+
+    pub async fn nonce_lane(&self, network: &str, address: &str, lane: &str) -> Result<Option<NonceLane>> {
+        nonce_lanes::get(self.writer(), network, address, lane).await
+    }
+
+    /// The lane reserving `nonce` of `address`; ordinary sends must not use it.
+    pub async fn nonce_lane_covering(&self, network: &str, address: &str, nonce: u64) -> Result<Option<NonceLane>> {
+        let mut conn = self.writer().acquire().await?;
+        nonce_lanes::covering(&mut conn, network, address, nonce).await
+    }
+
+    /// Closes the time-lock lane of `address` unless a lock still holds one
+    /// of its nonces.
+    pub async fn close_idle_nonce_lane(&self, network: &str, address: &str) -> Result<()> {
+        timelocks::close_idle_lane(self.writer(), network, address).await
+    }
+
+    pub async fn create_timelock(&self, lock: &NewTimelock<'_>) -> Result<()> {
+        timelocks::insert(self.writer(), lock, self.now().timestamp()).await
+    }
+
+    pub async fn timelock(&self, id: &str) -> Result<Option<TimelockRecord>> {
+        timelocks::get(self.writer(), id).await
+    }
+
+    pub async fn wallet_timelocks(&self, wallet_name: &str) -> Result<Vec<TimelockRecord>> {
+        timelocks::list_for_wallet(self.reader(), wallet_name).await
+    }
+
+    /// Moves a locked lock to `status`, closing its lane when it was the
+    /// last lock holding one; `false` if it was no longer locked.
+    pub async fn finish_timelock(
+        &self,
+        id: &str,
+        status: &str,
+        reason: Option<&str>,
+        tx_hash: Option<&str>,
+    ) -> Result<bool> {
+        timelocks::finish(self.writer(), id, status, reason, tx_hash, self.now().timestamp()).await
+    }
+
+    /// Expires the locks past their `expires_at`.
+    pub async fn expire_overdue_timelocks(&self) -> Result<Vec<TimelockRecord>> {
+        timelocks::expire_overdue(self.writer(), self.now().timestamp()).await
+    }
+}
+
+// WAL API
+impl WalletStorage {
+    /// Holds a writer connection for a checkpoint cycle.
+    pub async fn wal_session(&self) -> Result<WalSession> {
+        WalSession::acquire(self.writer()).await
+    }
+
+    pub async fn database_file_stats(&self) -> Result<DatabaseFileStats> {
+        wal::file_stats(&mut *self.writer().acquire().await?).await
+    }
+}
+
+// Events journal API
+impl WalletStorage {
+    fn journal_committed(&self, seq: i64) {
+        self.journal_tip.send_if_modified(|tip| {
+            if seq > *tip {
+                *tip = seq;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// Changes whenever an event commits through this storage handle (or a
+    /// clone of it). Writers in other processes are only seen on the next read.
+    pub fn subscribe_journal(&self) -> tokio::sync::watch::Receiver<i64> {
+        self.journal_tip.subscribe()
+    }
+
+    /// Journals an event that has no row change of its own (e.g. security events).
+    pub async fn record_event(&self, event: &NewJournalEvent<'_>) -> Result<i64> {
+        let mut tx = self.writer().begin().await?;
+        let seq = events_journal::append(&mut tx, event, self.now().timestamp()).await?;
+        tx.commit().await?;
+        self.journal_committed(seq);
+        Ok(seq)
+    }
+
+    /// Events with `seq > after_seq` in sequence order. A replica is only
+    /// used once it has replayed everything this process committed, so a
+    /// consumer woken by [`WalletStorage::subscribe_journal`] sees the event.
+    pub async fn journal_events(&self, after_seq: i64, limit: i64) -> Result<Vec<JournalEvent>> {
+        let pool = self.reader_for(ReadConsistency::AfterSeq(self.write_watermark())).await?;
+        events_journal::list_after(pool, after_seq, limit).await
+    }
+
+    pub async fn rename_wallet(&self, name: &str, new_name: &str) -> Result<()> {
+        let mut tx = self.writer().begin().await?;
+        let wallet_id: Option<String> = sqlx::query_scalar(
+            "UPDATE wallets SET name = ?1, updated_at = ?2 WHERE name = ?3 RETURNING id",
+        )
+        .bind(new_name)
+        .bind(self.now().naive_utc())
+        .bind(name)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to rename wallet: {}", e))?;
+        let wallet_id = wallet_id.ok_or_else(|| anyhow::anyhow!("Wallet not found: {}", name))?;
+        wallet_networks::rename(&mut tx, name, new_name).await?;
+        history_backfill::rename(&mut tx, name, new_name).await?;
+        deadman_switches::rename(&mut tx, name, new_name).await?;
+        delegations::rename(&mut tx, name, new_name).await?;
+        attestations::rename(&mut tx, name, new_name).await?;
+        address_book::rename(&mut tx, name, new_name).await?;
+        erc20_tokens::rename(&mut tx, name, new_name).await?;
+        timelocks::rename(&mut tx, name, new_name).await?;
+        spending_limits::rename(&mut tx, name, new_name).await?;
+        if feature_flags::rename(&mut tx, name, new_name).await? {
+            cache_epochs::bump(&mut tx, cache_epochs::FEATURE_FLAGS, self.now().timestamp()).await?;
+        }
+
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::WALLET_RENAMED,
+                entity_type: "wallet",
+                entity_id: &wallet_id,
+                payload: serde_json::json!({ "old_name": name, "new_name": new_name }),
+            },
+            self.now().timestamp(),
+        )
+        .await?;
+        cache_epochs::bump(&mut tx, cache_epochs::WALLETS, self.now().timestamp()).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to rename wallet: {}", e))?;
+        self.wallet_cache.flush();
+        self.journal_committed(seq);
+        Ok(())
+    }
+}
+
+// Sandbox databases
+impl WalletStorage {
+    /// Whether this database carries the `sandbox` system setting
+    pub async fn is_sandbox(&self) -> Result<bool> {
+        sandbox::is_sandbox(self.writer()).await
+    }
+
+    pub async fn mark_sandbox(&self) -> Result<()> {
+        let mut conn = self.writer().acquire().await?;
+        sandbox::mark(&mut conn, self.now().timestamp()).await
+    }
+
+    /// Copies the records of `wallet` from `source` with their addresses
+    /// rewritten, in one transaction that also marks this database as a
+    /// sandbox. `target_id` is the id of the sandbox wallet of the same name;
+    /// rows filed under the source wallet id move to it. Audit rows lose their
+    /// IP address and user agent and are re-MACed under this database's key.
+    /// Returns the rows written per table.
+    pub async fn import_sandbox_wallet(
+        &self,
+        source: &CloneSource,
+        wallet: &SourceWallet,
+        target_id: &str,
+        rewrites: &AddressRewrites,
+    ) -> Result<std::collections::BTreeMap<&'static str, u64>> {
+        let mut conn = self.writer().acquire().await?;
+        sqlx::query(&format!("ATTACH DATABASE ?1 AS {}", sandbox::SOURCE_SCHEMA))
+            .bind(source.path().to_string_lossy().into_owned())
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to attach source database: {}", e))?;
+        let copied = self.import_attached(&mut conn, wallet, target_id, rewrites).await;
+        // Detach even when the copy failed: the connection goes back to the pool
+        let detached = sqlx::query(&format!("DETACH DATABASE {}", sandbox::SOURCE_SCHEMA)).execute(&mut *conn).await;
+        let copied = copied?;
+        detached?;
+        self.wallet_cache.flush();
+        Ok(copied)
+    }
+
+    async fn import_attached(
+        &self,
+        conn: &mut sqlx::SqliteConnection,
+        wallet: &SourceWallet,
+        target_id: &str,
+        rewrites: &AddressRewrites,
+    ) -> Result<std::collections::BTreeMap<&'static str, u64>> {
+        use sqlx::Connection;
+        let present = sandbox::source_tables(conn).await?;
+        sandbox::load_rewrites(conn, rewrites).await?;
+        let now = self.now().timestamp();
+        let mut tx = conn.begin().await?;
+        let mut counts = sandbox::copy_rows(&mut tx, &present, &wallet.name, now).await?;
+        if present.contains("multisig_policies") {
+            counts.insert("multisig_policies", sandbox::copy_multisig_policies(&mut tx, &wallet.name, rewrites).await?);
+        }
+
+        // Integrity hashes cover the addresses, so transactions are rewritten here
+        let records: Vec<TransactionRecord> = sqlx::query_as(&format!(
+            "SELECT * FROM {}.transactions WHERE wallet_id IN (?1, ?2) ORDER BY created_at, id",
+            sandbox::SOURCE_SCHEMA
+        ))
+        .bind(&wallet.name)
+        .bind(&wallet.id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for mut record in records.iter().cloned() {
+            // sources from before version 18 may key rows by name
+            record.wallet_id = target_id.to_string();
+            record.from_address = rewrites.address(&record.network, &record.from_address);
+            record.to_address = rewrites.address(&record.network, &record.to_address);
+            let integrity_hash = Self::calculate_transaction_integrity_hash(&record);
+            sqlx::query(
+                r#"
+                INSERT INTO transactions (id, wallet_id, tx_hash, network, from_address, to_address, amount, fee,
+                    status, created_at, confirmed_at, integrity_hash, fee_actual, block_number)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+                ON CONFLICT(id) DO UPDATE SET
+                    wallet_id = excluded.wallet_id, tx_hash = excluded.tx_hash, network = excluded.network,
+                    from_address = excluded.from_address, to_address = excluded.to_address,
+                    amount = excluded.amount, fee = excluded.fee, status = excluded.status,
+                    created_at = excluded.created_at, confirmed_at = excluded.confirmed_at,
+                    integrity_hash = excluded.integrity_hash, fee_actual = excluded.fee_actual,
+                    block_number = excluded.block_number
+                "#,
+            )
+            .bind(&record.id)
+            .bind(&record.wallet_id)
+            .bind(&record.tx_hash)
+            .bind(&record.network)
+            .bind(&record.from_address)
+            .bind(&record.to_address)
+            .bind(&record.amount)
+            .bind(&record.fee)
+            .bind(&record.status)
+            .bind(record.created_at)
+            .bind(record.confirmed_at)
+            .bind(integrity_hash)
+            .bind(&record.fee_actual)
+            .bind(record.block_number)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to copy transaction {}: {}", record.id, e))?;
+        }
+        counts.insert("transactions", records.len() as u64);
+
+        // Audit ids are per database; a row already copied by an earlier run
+        // is recognised by its content and timestamp.
+        let logs: Vec<AuditLog> = sqlx::query_as(&format!(
+            "SELECT * FROM {}.audit_logs WHERE wallet_id IN (?1, ?2) ORDER BY id",
+            sandbox::SOURCE_SCHEMA
+        ))
+        .bind(&wallet.name)
+        .bind(&wallet.id)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut audited = 0;
+        for log in &logs {
+            let wallet_id = match log.wallet_id.as_deref() {
+                Some(id) if id == wallet.id => target_id,
+                _ => wallet.name.as_str(),
+            };
+            let details = rewrites.text(log.details.as_deref().unwrap_or_default());
+            let created_at = log.created_at.naive_utc();
+            let copied: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM audit_logs WHERE wallet_id = ?1 AND action = ?2 AND details = ?3 \
+                 AND created_at = ?4)",
+            )
+            .bind(wallet_id)
+            .bind(&log.action)
+            .bind(&details)
+            .bind(created_at)
+            .fetch_one(&mut *tx)
+            .await?;
+            if !copied {
+                self.insert_audit_at(&mut tx, wallet_id, &log.action, &details, None, None, created_at).await?;
+                audited += 1;
+            }
+        }
+        counts.insert("audit_logs", audited);
+
+        sandbox::mark(&mut tx, now).await?;
+        cache_epochs::bump(&mut tx, cache_epochs::WALLETS, now).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to import wallet {}: {}", wallet.name, e))?;
+        Ok(counts)
+    }
+}
+
+// Wallet description and metadata
+impl WalletStorage {
+    /// Description and metadata of `name`; `None` when the wallet does not exist.
+    pub async fn wallet_notes(&self, name: &str) -> Result<Option<WalletNotes>> {
+        wallet_notes::get(self.reader(), name).await
+    }
+
+    /// Notes of the wallets in `names` that have any, keyed by name
+    pub async fn wallet_notes_for(&self, names: &[String]) -> Result<HashMap<String, WalletNotes>> {
+        wallet_notes::for_names(self.reader(), names).await
+    }
+
+    /// Merge-patches the metadata of `name` and optionally replaces its
+    /// description; see [`NotesUpdate`] for the rejected outcomes.
+    pub async fn update_wallet_notes(
+        &self,
+        name: &str,
+        description: Option<Option<String>>,
+        patch: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<NotesUpdate> {
+        wallet_notes::update(self.writer(), name, description, patch, self.now().naive_utc()).await
+    }
+
+    /// Replaces the notes of `name` wholesale; returns whether the wallet exists.
+    pub async fn set_wallet_notes(&self, name: &str, notes: &WalletNotes) -> Result<bool> {
+        wallet_notes::set(self.writer(), name, notes, self.now().timestamp()).await
+    }
+
+    /// Names of wallets whose name, description or metadata values contain `query`
+    pub async fn search_wallets(&self, query: &str) -> Result<Vec<String>> {
+        wallet_notes::search(self.reader(), query).await
+    }
+}
+
+// Schema version
+impl WalletStorage {
+    /// Highest schema version recorded in this database (see
+    /// [`SCHEMA_VERSION`] for the one this build applies).
+    pub async fn schema_version(&self) -> Result<i64> {
+        schema_migrations::current(self.writer()).await
+    }
+}
+
+// Cache epochs
+impl WalletStorage {
+    /// Epoch of the wallet rows; advances in every transaction that changes
+    /// a wallet's name, notes, creation state or key material.
+    pub async fn wallets_epoch(&self) -> Result<i64> {
+        cache_epochs::read(self.writer(), cache_epochs::WALLETS).await
+    }
+
+    /// Epoch of the bridge transfer rows; advances on insert and status change.
+    pub async fn bridge_transactions_epoch(&self) -> Result<i64> {
+        cache_epochs::read(self.writer(), cache_epochs::BRIDGE_TRANSACTIONS).await
+    }
+
+    /// Epoch of the feature flag rules; advances on every rule change,
+    /// including rules moved or dropped with their wallet.
+    pub async fn feature_flags_epoch(&self) -> Result<i64> {
+        cache_epochs::read(self.writer(), cache_epochs::FEATURE_FLAGS).await
+    }
+}
+
+// Wallet metadata cache
+impl WalletStorage {
+    /// Replaces the wallet metadata cache (and drops whatever it held).
+    pub fn with_wallet_cache(mut self, settings: WalletCacheSettings) -> Self {
+        self.wallet_cache = Arc::new(wallet_cache::WalletCache::new(settings));
+        self
+    }
+
+    /// Flushes the cache when another instance changed wallets since the last
+    /// check. The epoch is read from the writer: a lagging replica would hide
+    /// exactly the changes this exists to detect.
+    async fn sync_wallet_cache(&self) {
+        let now = self.now();
+        if !self.wallet_cache.epoch_check_due(now) {
+            return;
+        }
+        match cache_epochs::read(self.writer(), cache_epochs::WALLETS).await {
+            Ok(epoch) => self.wallet_cache.observe_epoch(epoch, now),
+            Err(e) => {
+                warn!("wallet cache: epoch check failed, entries expire by TTL only: {}", e);
+                self.wallet_cache.epoch_read_failed(now);
+            }
+        }
+    }
+
+    /// [`WalletStorage::list_wallets`] served from the cache when it is
+    /// current. Changes made by other instances show up within the epoch
+    /// check interval, or within the TTL while the epoch cannot be read.
+    /// Misses load from the writer like the epoch itself.
+    pub async fn cached_wallet_list(&self) -> Result<Arc<Vec<WalletMetadata>>> {
+        self.sync_wallet_cache().await;
+        if let Some(list) = self.wallet_cache.list(self.now()) {
+            return Ok(list);
+        }
+        let generation = self.wallet_cache.generation();
+        let list = Arc::new(self.collect_wallets(self.writer()).await?);
+        self.wallet_cache.put_list(list.clone(), self.now(), generation);
+        Ok(list)
+    }
+
+    /// Metadata of a complete wallet, with the freshness of
+    /// [`WalletStorage::cached_wallet_list`].
+    pub async fn cached_wallet(&self, name: &str) -> Result<Option<WalletMetadata>> {
+        self.sync_wallet_cache().await;
+        if let Some(wallet) = self.wallet_cache.wallet(name, self.now()) {
+            return Ok(wallet);
+        }
+        let generation = self.wallet_cache.generation();
+        let wallet = wallet_page::by_name(self.writer(), name).await?;
+        self.wallet_cache.put_wallet(name, wallet.clone(), self.now(), generation);
+        Ok(wallet)
+    }
+
+    pub fn wallet_cache_stats(&self) -> WalletCacheStats {
+        self.wallet_cache.stats()
+    }
+}
+
+// Read/write splitting
+impl WalletStorage {
+    /// Routes read-only queries to a replica opened read-only at `database_url`
+    /// (a sqlite file kept in sync with the writer, or the writer's own file
+    /// for a separate set of reader connections).
+    pub async fn with_read_replica(self, database_url: &str) -> Result<Self> {
+        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+        use std::str::FromStr;
+
+        if !database_url.starts_with("sqlite:") {
+            return Err(anyhow::anyhow!("read_database_url must be a sqlite URL"));
+        }
+        let db_url = if database_url.starts_with("sqlite://") {
+            database_url.to_string()
+        } else {
+            database_url.replacen("sqlite:", "sqlite://", 1)
+        };
+        let connect_options = SqliteConnectOptions::from_str(&db_url)
+            .map_err(|e| anyhow::anyhow!("Invalid read database URL: {}", e))?
+            .read_only(true);
+        let read_pool = SqlitePoolOptions::new()
+            .max_connections(20)
+            .acquire_timeout(std::time::Duration::from_secs(30))
+            .idle_timeout(std::time::Duration::from_secs(600))
+            .connect_with(connect_options)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to read replica: {}", e))?;
+        info!("Read replica attached");
+        Ok(self.with_read_pool(read_pool))
+    }
+
+    /// Routes read-only queries to `read_pool`.
+    pub fn with_read_pool(mut self, read_pool: SqlitePool) -> Self {
+        self.read_pool = Some(read_pool);
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<crate::monitoring::WalletMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Replaces the per-class budgets of guarded queries.
+    pub fn with_query_timeouts(mut self, timeouts: QueryTimeouts) -> Self {
+        self.query_timeouts = timeouts;
+        self
+    }
+
+    /// Replaces the clock used for timestamps this storage writes.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Replaces the generator of new record ids.
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Makes every wallet creation hit `fault` (crash-recovery tests).
+    pub fn with_creation_fault(mut self, fault: CreationFault) -> Self {
+        self.creation_fault = Some(fault);
+        self
+    }
+
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    pub fn id_generator(&self) -> &Arc<dyn IdGenerator> {
+        &self.ids
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    pub fn has_read_replica(&self) -> bool {
+        self.read_pool.is_some()
+    }
+
+    /// Journal sequence of the latest commit made through this handle. A
+    /// replica that has replayed this far has every write this process made.
+    pub fn write_watermark(&self) -> i64 {
+        *self.journal_tip.borrow()
+    }
+
+    fn writer(&self) -> &SqlitePool {
+        self.count_operation(DbPool::Writer);
+        &self.pool
+    }
+
+    /// The replica when configured, otherwise the writer
+    fn reader(&self) -> &SqlitePool {
+        match &self.read_pool {
+            Some(read_pool) => {
+                self.count_operation(DbPool::Reader);
+                read_pool
+            }
+            None => self.writer(),
+        }
+    }
+
+    /// Pool for a read that needs `consistency`. `AfterSeq` checks the
+    /// replica's journal and falls back to the writer while it lags.
+    async fn reader_for(&self, consistency: ReadConsistency) -> Result<&SqlitePool> {
+        let Some(read_pool) = &self.read_pool else {
+            return Ok(self.writer());
+        };
+        match consistency {
+            ReadConsistency::Eventual => Ok(self.reader()),
+            ReadConsistency::Primary => Ok(self.writer()),
+            ReadConsistency::AfterSeq(seq) => {
+                let replayed = events_journal::last_seq(read_pool).await?;
+                if replayed >= seq {
+                    Ok(self.reader())
+                } else {
+                    debug!("read replica at seq {} behind {}, reading from writer", replayed, seq);
+                    Ok(self.writer())
+                }
+            }
+        }
+    }
+
+    /// Runs `query` on its own connection under the budget of `class`; see
+    /// [`query_guard::run`]. `name` labels the timing metric and logs.
+    async fn timed<T, F, Fut>(&self, pool: DbPool, name: &'static str, class: QueryClass, query: F) -> Result<T>
+    where
+        F: FnOnce(GuardedConnection) -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let (sqlite, wait) = match pool {
+            DbPool::Reader if self.read_pool.is_some() => (self.reader(), &self.reader_wait),
+            _ => (self.writer(), &self.writer_wait),
+        };
+        query_guard::run(sqlite, wait, &self.query_timeouts, self.metrics.as_deref(), name, class, query).await
+    }
+
+    /// Size, idle connections and the longest acquire wait of each pool since
+    /// the previous call.
+    pub fn pool_stats(&self) -> Vec<PoolStats> {
+        let mut stats = vec![PoolStats::sample(DbPool::Writer.as_str(), &self.pool, &self.writer_wait)];
+        if let Some(read_pool) = &self.read_pool {
+            stats.push(PoolStats::sample(DbPool::Reader.as_str(), read_pool, &self.reader_wait));
+        }
+        stats
+    }
+
+    fn count_operation(&self, pool: DbPool) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_database_pool_operation(pool.as_str());
+        }
+    }
+}
+
+// Cross-instance leases
+impl WalletStorage {
+    /// Holder id this handle acquires locks as
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Serialize nonce reservations across instances sharing this database
+    /// with a short lease per (network, address).
+    pub fn with_nonce_leases(mut self, ttl: std::time::Duration) -> Self {
+        self.nonce_lease = Some(ttl);
+        self
+    }
+
+    pub async fn try_acquire_lock(&self, name: &str, ttl: std::time::Duration) -> Result<bool> {
+        distributed_locks::try_acquire(self.writer(), name, &self.instance_id, ttl).await
+    }
+
+    pub async fn renew_lock(&self, name: &str, ttl: std::time::Duration) -> Result<bool> {
+        distributed_locks::renew(self.writer(), name, &self.instance_id, ttl).await
+    }
+
+    pub async fn release_lock(&self, name: &str) -> Result<bool> {
+        distributed_locks::release(self.writer(), name, &self.instance_id).await
+    }
+
+    pub async fn active_locks(&self) -> Result<Vec<LockRecord>> {
+        distributed_locks::active(self.writer()).await
+    }
+
+    /// Stops pending lease waits on every handle sharing this storage;
+    /// called once the server stops serving.
+    pub fn begin_shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    /// Waits for the nonce lease of (network, address) and returns the holder
+    /// id to release it with. The holder is unique per reservation so that
+    /// concurrent reservations from this instance exclude each other too. A
+    /// holder that died mid-reservation blocks others for at most one lease.
+    async fn acquire_nonce_lease(&self, name: &str, ttl: std::time::Duration) -> Result<String> {
+        enum LeaseWait {
+            Held,
+            Failed(anyhow::Error),
+        }
+        let holder = format!("{}/{}", self.instance_id, uuid::Uuid::new_v4());
+        let policy = RetryPolicy::new()
+            .with_max_attempts(u32::MAX)
+            .with_base_delay(std::time::Duration::from_millis(5))
+            .with_max_delay(std::time::Duration::from_millis(100))
+            .with_jitter(Jitter::Equal)
+            .with_deadline(ttl * 2)
+            .with_cancellation(self.shutdown.clone())
+            .retry_if(|e| matches!(e, LeaseWait::Held));
+        let outcome = retry(&policy, || async {
+            match distributed_locks::try_acquire(self.writer(), name, &holder, ttl).await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(LeaseWait::Held),
+                Err(e) => Err(LeaseWait::Failed(e)),
+            }
+        })
+        .await;
+        match outcome.result {
+            Ok(()) => Ok(holder),
+            Err(LeaseWait::Failed(e)) => Err(e),
+            Err(LeaseWait::Held) if outcome.stop == StopReason::Cancelled => {
+                Err(anyhow::anyhow!("nonce lease {} wait aborted by shutdown", name))
+            }
+            Err(LeaseWait::Held) => Err(anyhow::anyhow!("nonce lease {} is held by another instance", name)),
+        }
+    }
+
+    /// Runs `reserve` while holding the nonce lease of (network, address)
+    /// when leases are enabled.
+    async fn under_nonce_lease<T, F, Fut>(&self, network: &str, address: &str, reserve: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let Some(ttl) = self.nonce_lease else {
+            return reserve().await;
         };
-        Ok(tx)
+        // upsert + read-back is only atomic within one sqlite file; across
+        // instances the lease makes the pair exclusive
+        let lease = format!("nonce:{}:{}", network, address.to_lowercase());
+        let holder = self.acquire_nonce_lease(&lease, ttl).await?;
+        let reserved = reserve().await;
+        if let Err(e) = distributed_locks::release(self.writer(), &lease, &holder).await {
+            warn!("failed to release {}: {}", lease, e);
+        }
+        reserved
+    }
+
+    /// Reserves `count` consecutive nonces of `address` and returns the first.
+    ///
+    /// The range starts at the stored next nonce or at `floor`, whichever is
+    /// higher: callers pass the account's pending transaction count, so sends
+    /// that never went through this table cannot be handed out again. Nothing
+    /// is reserved when the range overlaps a nonce lane.
+    pub async fn reserve_nonce_range(&self, network: &str, address: &str, floor: u64, count: u64) -> Result<u64> {
+        if count == 0 {
+            return Err(anyhow::anyhow!("cannot reserve an empty nonce range"));
+        }
+        self.under_nonce_lease(network, address, || self.reserve_nonce_range_unleased(network, address, floor, count))
+            .await
+    }
+
+    async fn reserve_nonce_range_unleased(&self, network: &str, address: &str, floor: u64, count: u64) -> Result<u64> {
+        let now = self.now().naive_utc();
+        let reserved_at = self.now().timestamp();
+        self.timed(DbPool::Writer, "nonces.reserve_range", QueryClass::Hot, |mut conn| async move {
+            let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+            sqlx::query(
+                r#"
+                INSERT INTO nonces (network, address, next_nonce, updated_at)
+                VALUES (?1, ?2, ?3 + ?4, ?5)
+                ON CONFLICT(network, address)
+                DO UPDATE SET next_nonce = MAX(next_nonce, ?3) + ?4, updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(network)
+            .bind(address)
+            .bind(floor as i64)
+            .bind(count as i64)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("upsert nonce range failed: {}", e))?;
+
+            let row = sqlx::query("SELECT next_nonce FROM nonces WHERE network = ?1 AND address = ?2")
+                .bind(network)
+                .bind(address)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("select nonce failed: {}", e))?;
+            let end = row.get::<i64, _>("next_nonce") as u64;
+            let start = end - count;
+            if let Some(lane) = nonce_lanes::overlapping(&mut tx, network, address, start, end).await? {
+                return Err(anyhow::anyhow!(
+                    "nonces [{}, {}) of {} on {} overlap the {} lane [{}, {})",
+                    start,
+                    end,
+                    address,
+                    network,
+                    lane.lane,
+                    lane.start_nonce,
+                    lane.end_nonce
+                ));
+            }
+            nonce_reservations::record(&mut tx, network, address, start, end, reserved_at).await?;
+            tx.commit().await?;
+            Ok(start)
+        })
+        .await
+    }
+
+    /// Gives the unused tail `[from, end)` of a range from
+    /// [`reserve_nonce_range`](Self::reserve_nonce_range) back. Returns `false`
+    /// when a later reservation already moved past `end`; the tail stays
+    /// reserved then.
+    pub async fn release_nonce_range(&self, network: &str, address: &str, from: u64, end: u64) -> Result<bool> {
+        let now = self.now().naive_utc();
+        self.timed(DbPool::Writer, "nonces.release_range", QueryClass::Hot, |mut conn| async move {
+            let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+            let result = sqlx::query(
+                "UPDATE nonces SET next_nonce = ?1, updated_at = ?2 \
+                 WHERE network = ?3 AND address = ?4 AND next_nonce = ?5",
+            )
+            .bind(from as i64)
+            .bind(now)
+            .bind(network)
+            .bind(address)
+            .bind(end as i64)
+            .execute(&mut *tx)
+            .await?;
+            if result.rows_affected() != 1 {
+                return Ok(false);
+            }
+            nonce_reservations::forget(&mut tx, network, address, from, end).await?;
+            tx.commit().await?;
+            Ok(true)
+        })
+        .await
+    }
+
+    /// Reserved nonces of `address` the chain has not counted yet, lowest
+    /// first. Call [`confirm_nonces_below`](Self::confirm_nonces_below) with
+    /// a fresh transaction count beforehand.
+    pub async fn list_reserved_unconfirmed_nonces(
+        &self,
+        network: &str,
+        address: &str,
+    ) -> Result<Vec<NonceReservation>> {
+        nonce_reservations::unconfirmed(self.writer(), network, address).await
+    }
+
+    /// Marks the reservations below `count`, the chain's transaction count of
+    /// `address`, confirmed. Returns how many were still unconfirmed.
+    pub async fn confirm_nonces_below(&self, network: &str, address: &str, count: u64) -> Result<u64> {
+        let now = self.now().timestamp();
+        self.timed(DbPool::Writer, "nonces.confirm", QueryClass::Hot, |mut conn| async move {
+            nonce_reservations::confirm_below(&mut conn, network, address, count, now).await
+        })
+        .await
+    }
+
+    /// The stored next nonce of `address`; `None` before its first reservation.
+    pub async fn next_nonce(&self, network: &str, address: &str) -> Result<Option<u64>> {
+        let next = sqlx::query_scalar::<_, i64>("SELECT next_nonce FROM nonces WHERE network = ?1 AND address = ?2")
+            .bind(network)
+            .bind(address)
+            .fetch_optional(self.writer())
+            .await?;
+        Ok(next.map(|n| n as u64))
+    }
+
+    /// Gives an unconfirmed reserved `nonce` back to the counter, so the next
+    /// reservation hands it out again. Only the most recent reservation can
+    /// be given back; returns `false`, changing nothing, for any other nonce.
+    /// Release from the top down to roll back several.
+    pub async fn release_nonce(&self, network: &str, address: &str, nonce: u64) -> Result<bool> {
+        self.under_nonce_lease(network, address, || self.release_nonce_unleased(network, address, nonce)).await
+    }
+
+    async fn release_nonce_unleased(&self, network: &str, address: &str, nonce: u64) -> Result<bool> {
+        let now = self.now().naive_utc();
+        self.timed(DbPool::Writer, "nonces.release", QueryClass::Hot, |mut conn| async move {
+            let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+            if nonce_reservations::forget(&mut tx, network, address, nonce, nonce + 1).await? == 0 {
+                return Ok(false);
+            }
+            let result = sqlx::query(
+                "UPDATE nonces SET next_nonce = ?1, updated_at = ?2 \
+                 WHERE network = ?3 AND address = ?4 AND next_nonce = ?5",
+            )
+            .bind(nonce as i64)
+            .bind(now)
+            .bind(network)
+            .bind(address)
+            .bind(nonce as i64 + 1)
+            .execute(&mut *tx)
+            .await?;
+            if result.rows_affected() != 1 {
+                return Ok(false);
+            }
+            tx.commit().await?;
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn reserve_nonce_unleased(&self, network: &str, address: &str, initial: u64) -> Result<u64> {
+        // Use SQLite UPSERT to atomically increment next_nonce when row exists,
+        // otherwise insert a seeded next_nonce = initial+1. After the upsert,
+        // read the stored next_nonce and return next_nonce - 1 as the reserved
+        // nonce.
+
+        // Perform upsert: if row exists, increment next_nonce; else insert initial+1
+        let now = self.now().naive_utc();
+        let reserved_at = self.now().timestamp();
+        let seed = (initial as i64) + 1;
+        self.timed(DbPool::Writer, "nonces.reserve", QueryClass::Hot, |mut conn| async move {
+            let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+            // SQLite UPSERT syntax
+            let upsert_sql = r#"
+                INSERT INTO nonces (network, address, next_nonce, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(network, address)
+                DO UPDATE SET next_nonce = next_nonce + 1, updated_at = excluded.updated_at
+            "#;
+            sqlx::query(upsert_sql)
+                .bind(network)
+                .bind(address)
+                .bind(seed)
+                .bind(now)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("upsert nonce failed: {}", e))?;
+
+            // Read back the stored next_nonce
+            let row = sqlx::query("SELECT next_nonce FROM nonces WHERE network = ?1 AND address = ?2")
+                .bind(network)
+                .bind(address)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("select nonce failed: {}", e))?;
+            let next_nonce: i64 = row.get("next_nonce");
+            // reserved is next_nonce - 1
+            let reserved = (next_nonce - 1) as u64;
+            // a nonce inside a reserved lane is refused; dropping `tx` rolls the increment back
+            if let Some(lane) = nonce_lanes::covering(&mut tx, network, address, reserved).await? {
+                return Err(anyhow::anyhow!(
+                    "nonce {} of {} on {} is reserved by the {} lane [{}, {})",
+                    reserved,
+                    address,
+                    network,
+                    lane.lane,
+                    lane.start_nonce,
+                    lane.end_nonce
+                ));
+            }
+            nonce_reservations::record(&mut tx, network, address, reserved, reserved + 1, reserved_at).await?;
+            tx.commit().await?;
+            Ok(reserved)
+        })
+        .await
+    }
+}
+
+// ERC-4337 UserOperation tracking
+impl WalletStorage {
+    pub async fn record_user_operation(&self, op: &NewUserOperation<'_>) -> Result<()> {
+        user_operations::insert(self.writer(), op, self.now().timestamp()).await
+    }
+
+    pub async fn user_operation(&self, user_op_hash: &str) -> Result<Option<UserOperationRecord>> {
+        user_operations::get(self.writer(), user_op_hash).await
+    }
+
+    /// Marks a pending op included (`status` is [`USER_OP_INCLUDED`] or
+    /// [`USER_OP_REVERTED`]) and stores its bundle as `record`, atomically.
+    /// Returns `false`, writing nothing, when the op was already completed.
+    pub async fn complete_user_operation(
+        &self,
+        user_op_hash: &str,
+        status: &str,
+        actual_gas_cost: &str,
+        error: Option<&str>,
+        record: &TransactionRecord,
+    ) -> Result<bool> {
+        let mut tx = self.writer().begin().await?;
+        let now = self.now().timestamp();
+        if !user_operations::mark_included(&mut tx, user_op_hash, status, &record.tx_hash, actual_gas_cost, error, now)
+            .await?
+        {
+            return Ok(false);
+        }
+        let seq = self.insert_transaction(&mut tx, record).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to complete user operation: {}", e))?;
+        self.journal_committed(seq);
+        Ok(true)
+    }
+}
+
+// Meta-transaction relay API
+impl WalletStorage {
+    /// Takes one relay from `from_address`'s quota; `false` when exhausted.
+    pub async fn relay_consume_quota(
+        &self,
+        from_address: &str,
+        limit: u32,
+        window_secs: u64,
+    ) -> Result<bool> {
+        meta_tx_relays::consume_quota(self.writer(), from_address, limit, window_secs, self.now().timestamp())
+            .await
+    }
+
+    pub async fn relay_release_quota(&self, from_address: &str) -> Result<()> {
+        meta_tx_relays::release_quota(self.writer(), from_address).await
+    }
+
+    /// `false` if the same forward request is already pending or submitted.
+    pub async fn reserve_meta_tx_relay(&self, relay: &NewMetaTxRelay<'_>) -> Result<bool> {
+        meta_tx_relays::reserve(self.writer(), relay, self.now().timestamp()).await
+    }
+
+    pub async fn mark_meta_tx_submitted(&self, id: &str, tx_hash: &str) -> Result<()> {
+        meta_tx_relays::mark_submitted(self.writer(), id, tx_hash, self.now().timestamp()).await
+    }
+
+    pub async fn mark_meta_tx_failed(&self, id: &str, error: &str) -> Result<()> {
+        meta_tx_relays::mark_failed(self.writer(), id, error, self.now().timestamp()).await
+    }
+
+    pub async fn meta_tx_relays_for(
+        &self,
+        from_address: &str,
+        limit: i64,
+    ) -> Result<Vec<MetaTxRelayRecord>> {
+        meta_tx_relays::list_for_sender(self.writer(), from_address, limit).await
+    }
+}
+
+// Wallet-scoped token API
+impl WalletStorage {
+    pub async fn insert_wallet_token(&self, token: &NewWalletToken<'_>) -> Result<()> {
+        wallet_tokens::insert(self.writer(), token, self.now().timestamp()).await
+    }
+
+    /// Unrevoked bearer token by plaintext; may be expired.
+    pub async fn find_wallet_token(&self, token: &str) -> Result<Option<WalletTokenRecord>> {
+        wallet_tokens::find_active(self.writer(), &hash_token(token)).await
+    }
+
+    /// Unrevoked token of either mode by id; may be expired.
+    pub async fn find_wallet_token_by_id(&self, id: &str) -> Result<Option<WalletTokenRecord>> {
+        wallet_tokens::find_active_by_id(self.writer(), id).await
+    }
+
+    /// Unrevoked HMAC signing key and its sealed secret; may be expired.
+    pub async fn find_signing_key(&self, id: &str) -> Result<Option<(WalletTokenRecord, SealedSigningSecret)>> {
+        wallet_tokens::find_signing_key(self.writer(), id).await
+    }
+
+    /// Claims a signed request's nonce for `key_id` until `expires_at`; `false`
+    /// if another request holds it past `now` (both unix seconds, on the clock
+    /// the request timestamp was checked against).
+    pub async fn claim_request_nonce(&self, key_id: &str, nonce: &str, expires_at: i64, now: i64) -> Result<bool> {
+        request_nonces::claim(self.writer(), key_id, nonce, expires_at, now).await
+    }
+
+    pub async fn purge_request_nonces(&self, now: i64) -> Result<u64> {
+        request_nonces::purge_expired(self.writer(), now).await
+    }
+
+    pub async fn wallet_tokens_for(
+        &self,
+        owner_user_id: &str,
+        wallet_name: &str,
+    ) -> Result<Vec<WalletTokenRecord>> {
+        wallet_tokens::list_for_wallet(self.writer(), owner_user_id, wallet_name).await
+    }
+
+    /// `false` if no such unrevoked token exists on the wallet.
+    pub async fn revoke_wallet_token(
+        &self,
+        owner_user_id: &str,
+        wallet_name: &str,
+        id: &str,
+    ) -> Result<bool> {
+        wallet_tokens::revoke(self.writer(), owner_user_id, wallet_name, id, self.now().timestamp())
+            .await
+    }
+}
+
+// Delegated spending sessions
+impl WalletStorage {
+    pub async fn insert_delegation(&self, delegation: &NewDelegation<'_>) -> Result<DelegationRecord> {
+        let mut tx = self.writer().begin().await?;
+        delegations::insert(&mut tx, delegation, self.now().timestamp()).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store delegation: {}", e))?;
+        self.delegation(delegation.wallet_name, delegation.id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Delegation {} vanished after insert", delegation.id))
+    }
+
+    /// Unrevoked delegation by plaintext token; may be expired.
+    pub async fn find_delegation(&self, token: &str) -> Result<Option<DelegationRecord>> {
+        delegations::find_active(self.writer(), &hash_token(token)).await
+    }
+
+    pub async fn delegation(&self, wallet_name: &str, id: &str) -> Result<Option<DelegationRecord>> {
+        delegations::get(self.writer(), wallet_name, id).await
+    }
+
+    pub async fn delegations_for(&self, wallet_name: &str) -> Result<Vec<DelegationRecord>> {
+        delegations::list_for_wallet(self.writer(), wallet_name).await
+    }
+
+    /// `false` if no such unrevoked delegation exists on the wallet.
+    pub async fn revoke_delegation(&self, wallet_name: &str, id: &str) -> Result<bool> {
+        delegations::revoke(self.writer(), wallet_name, id, self.now().timestamp()).await
+    }
+
+    /// Reserves `amount` of the delegation's budget on `network` and writes the
+    /// `delegation.spend` audit row in the same transaction; nothing is
+    /// written unless the result is [`Reservation::Reserved`].
+    pub async fn reserve_delegation_budget(
+        &self,
+        delegation: &DelegationRecord,
+        network: &str,
+        to: &str,
+        amount: ethers::types::U256,
+    ) -> Result<Reservation> {
+        let mut tx = self.writer().begin().await?;
+        let reservation =
+            delegations::reserve(&mut tx, &delegation.id, network, amount, self.now().timestamp()).await?;
+        if !matches!(reservation, Reservation::Reserved { .. }) {
+            tx.rollback().await?;
+            return Ok(reservation);
+        }
+        let details = serde_json::json!({
+            "delegation_id": delegation.id,
+            "network": network,
+            "to": to,
+            "value": amount.to_string(),
+        });
+        self.insert_audit(&mut tx, &delegation.wallet_name, "delegation.spend", &details.to_string(), None, None)
+            .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to reserve delegation budget: {}", e))?;
+        Ok(reservation)
+    }
+
+    /// Gives back a reservation whose transaction was never broadcast,
+    /// audited as `delegation.refund`.
+    pub async fn release_delegation_budget(
+        &self,
+        delegation: &DelegationRecord,
+        network: &str,
+        amount: ethers::types::U256,
+        reason: &str,
+    ) -> Result<()> {
+        let mut tx = self.writer().begin().await?;
+        delegations::release(&mut tx, &delegation.id, network, amount).await?;
+        let details = serde_json::json!({
+            "delegation_id": delegation.id,
+            "network": network,
+            "value": amount.to_string(),
+            "reason": reason,
+        });
+        self.insert_audit(&mut tx, &delegation.wallet_name, "delegation.refund", &details.to_string(), None, None)
+            .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to release delegation budget: {}", e))?;
+        Ok(())
+    }
+}
+
+// User erasure
+impl WalletStorage {
+    /// Wallet-database half of erasing `user_id`, in one transaction: redacts
+    /// and re-MACs the audit rows of `wallet_names`, deletes the user's wallet
+    /// tokens and hands their groups and balance subscriptions to
+    /// `erased_owner`. The re-MAC is journaled as `audit.remaced` with the ids
+    /// of the rows whose MAC was replaced.
+    pub async fn erase_user_records(
+        &self,
+        user_id: &str,
+        wallet_names: &[String],
+        erased_owner: &str,
+    ) -> Result<WalletErasure> {
+        let now = self.now().timestamp();
+        let mut db_tx = self.writer().begin().await?;
+
+        // audit rows name a wallet either by name or by its wallets.id
+        let mut wallet_keys = wallet_names.to_vec();
+        for name in wallet_names {
+            let id: Option<String> = sqlx::query_scalar("SELECT id FROM wallets WHERE name = ?1")
+                .bind(name)
+                .fetch_optional(&mut *db_tx)
+                .await?;
+            wallet_keys.extend(id);
+        }
+
+        let redacted_audit_ids = erasure::redact_audit_rows(&mut db_tx, &wallet_keys).await?;
+        let api_tokens_deleted = erasure::delete_user_tokens(&mut db_tx, user_id).await?;
+        let ownership_transferred = erasure::transfer_ownership(&mut db_tx, user_id, erased_owner).await?;
+
+        let mut seq = None;
+        if !redacted_audit_ids.is_empty() {
+            seq = Some(
+                events_journal::append(
+                    &mut db_tx,
+                    &NewJournalEvent {
+                        event_type: events_journal::AUDIT_REMACED,
+                        entity_type: "user",
+                        entity_id: user_id,
+                        payload: serde_json::json!({
+                            "reason": "erasure",
+                            "audit_ids": redacted_audit_ids,
+                            "marker": ERASED_MARKER,
+                        }),
+                    },
+                    now,
+                )
+                .await?,
+            );
+        }
+        db_tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to erase user records: {}", e))?;
+        if let Some(seq) = seq {
+            self.journal_committed(seq);
+        }
+
+        Ok(WalletErasure { redacted_audit_ids, api_tokens_deleted, ownership_transferred })
+    }
+
+    /// Issues a certificate for an erasure of `user_id`, journaled as `user.erased`.
+    pub async fn record_erasure_certificate(
+        &self,
+        user_id: &str,
+        erased_by: &str,
+        categories: std::collections::BTreeMap<String, u64>,
+        wallets: Vec<String>,
+    ) -> Result<ErasureCertificate> {
+        let cert = ErasureCertificate {
+            id: self.ids.new_id(),
+            user_id: user_id.to_string(),
+            erased_by: erased_by.to_string(),
+            categories,
+            wallets,
+            created_at: self.now().timestamp(),
+        };
+        let mut db_tx = self.writer().begin().await?;
+        erasure::insert_certificate(&mut db_tx, &cert).await?;
+        let seq = events_journal::append(
+            &mut db_tx,
+            &NewJournalEvent {
+                event_type: events_journal::USER_ERASED,
+                entity_type: "user",
+                entity_id: user_id,
+                payload: serde_json::json!({
+                    "certificate_id": cert.id,
+                    "erased_by": cert.erased_by,
+                    "categories": cert.categories,
+                }),
+            },
+            cert.created_at,
+        )
+        .await?;
+        db_tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store erasure certificate: {}", e))?;
+        self.journal_committed(seq);
+        Ok(cert)
+    }
+
+    /// Certificates issued for `user_id`, oldest first.
+    pub async fn erasure_certificates(&self, user_id: &str) -> Result<Vec<ErasureCertificate>> {
+        erasure::list_for_user(self.writer(), user_id).await
+    }
+}
+
+// Multisig policy API
+impl WalletStorage {
+    /// Replaces the policy of `record.wallet_name` on `record.network`.
+    pub async fn put_multisig_policy(&self, record: &MultisigPolicyRecord) -> Result<()> {
+        multisig_policies::upsert(self.writer(), record).await
+    }
+
+    pub async fn get_multisig_policy(
+        &self,
+        wallet_name: &str,
+        network: &str,
+    ) -> Result<Option<MultisigPolicyRecord>> {
+        multisig_policies::get(self.writer(), wallet_name, network).await
+    }
+}
+
+// Signing intent API
+impl WalletStorage {
+    /// `false` if the nonce is held by another unreleased intent.
+    pub async fn begin_signing_intent(&self, intent: &NewSigningIntent<'_>) -> Result<bool> {
+        signing_intents::begin(self.writer(), intent, self.now().timestamp()).await
+    }
+
+    /// `false` if the intent was not in state `from` any more.
+    pub async fn advance_signing_intent(
+        &self,
+        id: &str,
+        from: &str,
+        to: &str,
+        tx_hash: Option<&str>,
+    ) -> Result<bool> {
+        signing_intents::transition(self.writer(), id, from, to, tx_hash, None, self.now().timestamp())
+            .await
+    }
+
+    /// Moves the intent to `released`, giving its nonce back.
+    pub async fn release_signing_intent(&self, id: &str, from: &str, reason: &str) -> Result<bool> {
+        signing_intents::transition(
+            self.writer(),
+            id,
+            from,
+            INTENT_RELEASED,
+            None,
+            Some(reason),
+            self.now().timestamp(),
+        )
+        .await
+    }
+
+    pub async fn get_signing_intent(&self, id: &str) -> Result<Option<SigningIntentRecord>> {
+        signing_intents::get(self.writer(), id).await
+    }
+
+    /// Unresolved intents untouched for at least `min_age_secs`.
+    pub async fn unresolved_signing_intents(
+        &self,
+        min_age_secs: u64,
+        limit: i64,
+    ) -> Result<Vec<SigningIntentRecord>> {
+        let updated_before = self.now().timestamp() - min_age_secs as i64;
+        signing_intents::list_unresolved(self.writer(), updated_before, limit).await
+    }
+
+    pub async fn intent_transaction_recorded(&self, id: &str) -> Result<bool> {
+        signing_intents::transaction_recorded(self.writer(), id).await
+    }
+
+    /// Unresolved intent for the same transfer created in the last `window_secs`.
+    pub async fn in_flight_signing_intent(
+        &self,
+        wallet_name: &str,
+        network: &str,
+        to_address: &str,
+        value: &str,
+        window_secs: u64,
+    ) -> Result<Option<SigningIntentRecord>> {
+        let created_since = self.now().timestamp() - window_secs as i64;
+        signing_intents::find_in_flight(self.writer(), wallet_name, network, to_address, value, created_since)
+            .await
+    }
+
+    /// Unreleased intent holding `nonce` of `from_address` (lowercase hex).
+    pub async fn signing_intent_at_nonce(
+        &self,
+        network: &str,
+        from_address: &str,
+        nonce: i64,
+    ) -> Result<Option<SigningIntentRecord>> {
+        signing_intents::find_by_nonce(self.writer(), network, from_address, nonce).await
+    }
+}
+
+// Key rotation persistence API
+impl WalletStorage {
+    pub async fn rotation_upsert_label(
+        &self,
+        label: &str,
+        current_version: i64,
+        current_id: Option<&str>,
+    ) -> Result<()> {
+        key_rotation::upsert_label(self.writer(), label, current_version, current_id).await
+    }
+
+    pub async fn rotation_insert_version(
+        &self,
+        label: &str,
+        version: i64,
+        key_id: &str,
+    ) -> Result<()> {
+        key_rotation::insert_version(self.writer(), label, version, key_id, self.now().timestamp()).await
+    }
+
+    /// Retires `version` of `label`; journaled as a key rotation.
+    pub async fn rotation_mark_retired(&self, label: &str, version: i64) -> Result<()> {
+        let mut tx = self.writer().begin().await?;
+        key_rotation::mark_retired(&mut *tx, label, version).await?;
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::KEY_ROTATED,
+                entity_type: "signing_key",
+                entity_id: label,
+                payload: serde_json::json!({ "label": label, "retired_version": version }),
+            },
+            self.now().timestamp(),
+        )
+        .await?;
+        tx.commit().await?;
+        self.journal_committed(seq);
+        Ok(())
+    }
+
+    /// Re-seals every completed wallet in one transaction. `rewrap` sees each
+    /// envelope in name order and returns its replacement, or `None` when
+    /// the wallet cannot be rewrapped. The updates are always executed; the
+    /// transaction is committed (and journaled as `key.rotated` under
+    /// `kek_id`) only when `apply` is set and every wallet was rewrapped,
+    /// and rolled back otherwise, so a dry run touches no rows.
+    pub async fn rewrap_wallet_envelopes<F>(&self, apply: bool, kek_id: &str, mut rewrap: F) -> Result<RewrapSummary>
+    where
+        F: FnMut(&WalletEnvelope) -> Option<RewrappedEnvelope>,
+    {
+        let now = self.now();
+        let mut tx = self.writer().begin().await?;
+        let envelopes = key_rotation::envelopes(&mut tx).await?;
+        let mut rows_updated = 0;
+        let mut complete = true;
+        for envelope in &envelopes {
+            match rewrap(envelope) {
+                Some(rewrapped) => {
+                    rows_updated += key_rotation::replace_envelope(&mut tx, &envelope.name, &rewrapped, now.naive_utc())
+                        .await
+                        .map_err(|e| anyhow::anyhow!("Failed to rewrap wallet {}: {}", envelope.name, e))?;
+                }
+                None => complete = false,
+            }
+        }
+
+        if !apply || !complete {
+            tx.rollback().await?;
+            return Ok(RewrapSummary { rows_updated, committed: false });
+        }
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::KEY_ROTATED,
+                entity_type: "envelope_kek",
+                entity_id: kek_id,
+                payload: serde_json::json!({ "kek_id": kek_id, "wallets": rows_updated }),
+            },
+            now.timestamp(),
+        )
+        .await?;
+        cache_epochs::bump(&mut tx, cache_epochs::WALLETS, now.timestamp()).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to rewrap wallets: {}", e))?;
+        self.wallet_cache.flush();
+        self.journal_committed(seq);
+        Ok(RewrapSummary { rows_updated, committed: true })
+    }
+
+    pub async fn rotation_inc_usage(&self, label: &str, version: i64) -> Result<()> {
+        key_rotation::inc_usage(self.writer(), label, version).await
+    }
+
+    pub async fn rotation_inc_usage_by(&self, label: &str, version: i64, count: i64) -> Result<()> {
+        key_rotation::inc_usage_by(self.writer(), label, version, count).await
+    }
+
+    pub async fn rotation_get_label(
+        &self,
+        label: &str,
+    ) -> Result<Option<key_rotation::KeyLabelRecord>> {
+        key_rotation::get_label(self.writer(), label).await
+    }
+
+    pub async fn rotation_get_version(
+        &self,
+        label: &str,
+        version: i64,
+    ) -> Result<Option<key_rotation::KeyVersionRecord>> {
+        key_rotation::get_version(self.writer(), label, version).await
+    }
+}
+
+// Bridge Transaction Storage
+impl WalletStorage {
+    pub async fn store_bridge_transaction(&self, tx: &BridgeTransaction) -> Result<()> {
+        let status_str = serde_json::to_string(&tx.status)?;
+        let mut db_tx = self.writer().begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO bridge_transactions (id, from_wallet, from_chain, to_chain, token, amount, status,
+                source_tx_hash, destination_tx_hash, created_at, updated_at, fee_amount, estimated_completion_time,
+                amount_minimal_source, amount_minimal_dest, token_address_source, token_address_dest)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+            "#,
+        )
+        .bind(&tx.id)
+        .bind(&tx.from_wallet)
+        .bind(&tx.from_chain)
+        .bind(&tx.to_chain)
+        .bind(&tx.token)
+        .bind(&tx.amount)
+        .bind(status_str)
+        .bind(&tx.source_tx_hash)
+        .bind(&tx.destination_tx_hash)
+        .bind(tx.created_at)
+        .bind(tx.updated_at)
+        .bind(&tx.fee_amount)
+        .bind(tx.estimated_completion_time)
+        .bind(&tx.amount_minimal_source)
+        .bind(&tx.amount_minimal_dest)
+        .bind(&tx.token_address_source)
+        .bind(&tx.token_address_dest)
+        .execute(&mut *db_tx)
+        .await?;
+        cache_epochs::bump(&mut db_tx, cache_epochs::BRIDGE_TRANSACTIONS, self.now().timestamp()).await?;
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_bridge_transaction(&self, id: &str) -> Result<BridgeTransaction> {
+        let row = sqlx::query("SELECT * FROM bridge_transactions WHERE id = ?1")
+            .bind(id)
+            .fetch_one(self.writer())
+            .await?;
+        bridge_query::from_row(&row)
     }
 
     pub async fn update_bridge_transaction_status(
@@ -775,13 +4544,31 @@ impl WalletStorage {
         source_tx_hash: Option<String>,
     ) -> Result<()> {
         let status_str = serde_json::to_string(&status)?;
-        let now = Utc::now();
-        sqlx::query("UPDATE bridge_transactions SET status = ?1, updated_at = ?2, source_tx_hash = COALESCE(?3, source_tx_hash) WHERE id = ?4")
+        let now = self.now();
+        let mut tx = self.writer().begin().await?;
+        let result = sqlx::query("UPDATE bridge_transactions SET status = ?1, updated_at = ?2, source_tx_hash = COALESCE(?3, source_tx_hash) WHERE id = ?4")
             .bind(status_str)
             .bind(now)
-            .bind(source_tx_hash)
+            .bind(&source_tx_hash)
             .bind(id)
-            .execute(&self.pool).await?;
+            .execute(&mut *tx).await?;
+        if result.rows_affected() == 0 {
+            return Ok(());
+        }
+        let seq = events_journal::append(
+            &mut tx,
+            &NewJournalEvent {
+                event_type: events_journal::BRIDGE_STATUS_CHANGED,
+                entity_type: "bridge_transaction",
+                entity_id: id,
+                payload: serde_json::json!({ "status": status, "source_tx_hash": source_tx_hash }),
+            },
+            self.now().timestamp(),
+        )
+        .await?;
+        cache_epochs::bump(&mut tx, cache_epochs::BRIDGE_TRANSACTIONS, now.timestamp()).await?;
+        tx.commit().await?;
+        self.journal_committed(seq);
         Ok(())
     }
 
@@ -794,87 +4581,93 @@ impl WalletStorage {
         offset: usize,
         limit: usize,
     ) -> Result<(Vec<BridgeTransaction>, usize)> {
-        // Build count query with filters
-        let mut count_query = "SELECT COUNT(*) FROM bridge_transactions WHERE 1=1".to_string();
-        let mut conditions = Vec::new();
-        let mut params: Vec<&str> = Vec::new();
-        
-        if let Some(wallet) = wallet_name {
-            conditions.push("from_wallet = ?");
-            params.push(wallet);
-        }
-        if let Some(from) = from_chain {
-            conditions.push("from_chain = ?");
-            params.push(from);
-        }
-        if let Some(to) = to_chain {
-            conditions.push("to_chain = ?");
-            params.push(to);
-        }
-        
-        if !conditions.is_empty() {
-            count_query.push_str(" AND ");
-            count_query.push_str(&conditions.join(" AND "));
-        }
-        
-        // Count total
-        let mut count_stmt = sqlx::query_scalar::<_, i64>(&count_query);
-        for param in &params {
-            count_stmt = count_stmt.bind(param);
-        }
-        let total_row = count_stmt.fetch_one(&self.pool).await?;
-        let total = total_row as usize;
-        
-        // Build select query
-        let mut select_query = "SELECT * FROM bridge_transactions WHERE 1=1".to_string();
-        if !conditions.is_empty() {
-            select_query.push_str(" AND ");
-            select_query.push_str(&conditions.join(" AND "));
-        }
-        select_query.push_str(" ORDER BY created_at DESC LIMIT ? OFFSET ?");
-        
-        // Fetch paginated results
-        let mut select_stmt = sqlx::query(&select_query);
-        for param in &params {
-            select_stmt = select_stmt.bind(param);
-        }
-        let rows = select_stmt
-            .bind(limit as i64)
-            .bind(offset as i64)
-            .fetch_all(&self.pool)
-            .await?;
-        
-        let mut transactions = Vec::new();
-        for row in rows {
-            let status_str: String = row.get("status");
-            let status: BridgeTransactionStatus = serde_json::from_str(&status_str)
-                .map_err(|e| anyhow::anyhow!("Failed to parse bridge status: {}", e))?;
-            
-            transactions.push(BridgeTransaction {
-                id: row.get("id"),
-                from_wallet: row.get("from_wallet"),
-                from_chain: row.get("from_chain"),
-                to_chain: row.get("to_chain"),
-                token: row.get("token"),
-                amount: row.get("amount"),
-                status,
-                source_tx_hash: row.get("source_tx_hash"),
-                destination_tx_hash: row.get("destination_tx_hash"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-                fee_amount: row.get("fee_amount"),
-                estimated_completion_time: row.get("estimated_completion_time"),
-            });
-        }
-        
+        let filter = BridgeFilter { wallet_name, from_chain, to_chain };
+        // Count and page from the same pool so the total matches the rows
+        let pool = self.reader();
+        let total = bridge_query::count(pool, &filter).await?;
+        let transactions = bridge_query::offset_page(pool, &filter, offset, limit).await?;
         Ok((transactions, total))
     }
+
+    /// Keyset page of bridge transactions (newest first); see
+    /// [`bridge_query::keyset_page`].
+    pub async fn bridge_transactions_page(
+        &self,
+        filter: &BridgeFilter<'_>,
+        cursor: Option<&TransactionCursor>,
+        limit: usize,
+    ) -> Result<(Vec<BridgeTransaction>, Option<TransactionCursor>)> {
+        bridge_query::keyset_page(self.reader(), filter, cursor, limit).await
+    }
+
+    pub async fn count_bridge_transactions(&self, filter: &BridgeFilter<'_>) -> Result<usize> {
+        bridge_query::count(self.reader(), filter).await
+    }
+
+    /// Transfers the bridge poller still has to follow up on; see
+    /// [`bridge_query::pending`]. Read from the writer so a status the
+    /// poller just stored is not seen again as pending.
+    pub async fn list_pending_bridge_transactions(&self, limit: usize) -> Result<Vec<BridgeTransaction>> {
+        bridge_query::pending(self.writer(), limit).await
+    }
+
+    /// Per-route `(from_chain, to_chain, total, failed)` for bridge transfers
+    /// created since `since`; feeds route health in discovery.
+    pub async fn bridge_route_stats(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<(String, String, u64, u64)>> {
+        // status 以 JSON 存储，Failed 带原因：{"Failed":"..."}
+        let rows = self
+            .timed(DbPool::Reader, "bridge.route_stats", QueryClass::Analytics, |mut conn| async move {
+                Ok(sqlx::query(
+                    r#"
+                    SELECT from_chain, to_chain,
+                           COUNT(*) AS total,
+                           SUM(CASE WHEN status LIKE '{"Failed"%' THEN 1 ELSE 0 END) AS failed
+                    FROM bridge_transactions
+                    WHERE created_at >= ?1
+                    GROUP BY from_chain, to_chain
+                    "#,
+                )
+                .bind(since)
+                .fetch_all(&mut *conn)
+                .await?)
+            })
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let total: i64 = row.get("total");
+                let failed: i64 = row.get("failed");
+                (row.get("from_chain"), row.get("to_chain"), total as u64, failed as u64)
+            })
+            .collect())
+    }
 }
 
 impl Clone for WalletStorage {
     fn clone(&self) -> Self {
         // Clone the underlying pool
-        Self { pool: self.pool.clone(), is_memory: self.is_memory }
+        Self {
+            pool: self.pool.clone(),
+            read_pool: self.read_pool.clone(),
+            metrics: self.metrics.clone(),
+            is_memory: self.is_memory,
+            journal_tip: self.journal_tip.clone(),
+            instance_id: self.instance_id.clone(),
+            nonce_lease: self.nonce_lease,
+            shutdown: self.shutdown.clone(),
+            clock: self.clock.clone(),
+            ids: self.ids.clone(),
+            creation_fault: self.creation_fault,
+            wallet_cache: self.wallet_cache.clone(),
+            query_timeouts: self.query_timeouts,
+            writer_wait: self.writer_wait.clone(),
+            reader_wait: self.reader_wait.clone(),
+            send_locks: self.send_locks.clone(),
+        }
     }
 }
 
@@ -901,9 +4694,16 @@ pub struct TransactionRecord {
     pub created_at: DateTime<Utc>,
     pub confirmed_at: Option<DateTime<Utc>>,
     pub integrity_hash: String,
+    /// Fee actually paid (gas used × effective gas price), set from the receipt
+    /// by [`WalletStorage::finalize_transaction`]; `fee` keeps the estimate.
+    #[sqlx(default)]
+    pub fee_actual: Option<String>,
+    /// Block the transaction was mined in, set with `fee_actual`
+    #[sqlx(default)]
+    pub block_number: Option<i64>,
 }
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, FromRow, serde::Serialize)]
 pub struct AuditLog {
     pub id: i64,
     pub wallet_id: Option<String>,
@@ -914,6 +4714,50 @@ pub struct AuditLog {
     pub created_at: DateTime<Utc>,
 }
 
+fn is_postgres_url(database_url: &str) -> bool {
+    database_url.starts_with("postgres:") || database_url.starts_with("postgresql:")
+}
+
+/// Backend opened by [`open_backend`], still concrete so callers can configure it
+pub enum StorageBackend {
+    Sqlite(WalletStorage),
+    #[cfg(feature = "postgres")]
+    Postgres(PostgresWalletStorage),
+}
+
+impl StorageBackend {
+    /// Erases the backend behind [`WalletStorageTrait`]
+    pub fn into_shared(self) -> Arc<dyn WalletStorageTrait + Send + Sync> {
+        match self {
+            StorageBackend::Sqlite(storage) => Arc::new(storage),
+            #[cfg(feature = "postgres")]
+            StorageBackend::Postgres(storage) => Arc::new(storage),
+        }
+    }
+}
+
+/// Opens the storage backend named by the scheme of `database_url`:
+/// `sqlite:` gives a [`WalletStorage`] with `wal` applied, `postgres:` /
+/// `postgresql:` a `PostgresWalletStorage` when built with the `postgres` feature.
+pub async fn open_backend(database_url: &str, wal: WalSettings) -> Result<StorageBackend> {
+    if database_url.starts_with("sqlite:") {
+        return Ok(StorageBackend::Sqlite(WalletStorage::new_with_wal(database_url, wal).await?));
+    }
+    if is_postgres_url(database_url) {
+        #[cfg(feature = "postgres")]
+        return Ok(StorageBackend::Postgres(PostgresWalletStorage::connect(database_url).await?));
+        #[cfg(not(feature = "postgres"))]
+        return Err(anyhow::anyhow!("PostgreSQL storage needs a build with the `postgres` feature"));
+    }
+    let scheme = database_url.split_once(':').map_or("(none)", |(scheme, _)| scheme);
+    Err(anyhow::anyhow!("Unsupported storage backend: {}", scheme))
+}
+
+/// [`open_backend`] with default WAL settings, behind [`WalletStorageTrait`]
+pub async fn open_storage(database_url: &str) -> Result<Arc<dyn WalletStorageTrait + Send + Sync>> {
+    Ok(open_backend(database_url, WalSettings::default()).await?.into_shared())
+}
+
 #[async_trait]
 pub trait WalletStorageTrait {
     fn as_any(&self) -> &dyn Any;
@@ -953,6 +4797,23 @@ pub trait WalletStorageTrait {
     // increment the stored next_nonce value. If no row exists, callers may
     // provide `initial` to seed the value.
     async fn reserve_next_nonce(&self, network: &str, address: &str, initial: u64) -> Result<u64>;
+    // reserve_nonce_range returns the first of `count` consecutive nonces,
+    // starting no lower than `floor` (the account's pending transaction
+    // count). The default reserves them one at a time and fails if another
+    // reservation interleaved; implementations should do it in one step.
+    async fn reserve_nonce_range(&self, network: &str, address: &str, floor: u64, count: u64) -> Result<u64> {
+        if let Some(last_used) = floor.checked_sub(1) {
+            self.mark_nonce_used(network, address, last_used).await?;
+        }
+        let start = self.reserve_next_nonce(network, address, floor).await?;
+        for offset in 1..count {
+            let nonce = self.reserve_next_nonce(network, address, floor).await?;
+            if nonce != start + offset {
+                return Err(anyhow::anyhow!("nonce range of {} on {} interleaved at {}", address, network, nonce));
+            }
+        }
+        Ok(start)
+    }
     // mark a nonce as used; this will set next_nonce to max(next_nonce, nonce+1)
     async fn mark_nonce_used(&self, network: &str, address: &str, nonce: u64) -> Result<()>;
 }
@@ -1029,39 +4890,11 @@ impl WalletStorageTrait for WalletStorage {
     }
 
     async fn reserve_next_nonce(&self, network: &str, address: &str, initial: u64) -> Result<u64> {
-        // Use SQLite UPSERT to atomically increment next_nonce when row exists,
-        // otherwise insert a seeded next_nonce = initial+1. After the upsert,
-        // read the stored next_nonce and return next_nonce - 1 as the reserved
-        // nonce.
-
-        // Perform upsert: if row exists, increment next_nonce; else insert initial+1
-        let now = Utc::now().naive_utc();
-        let seed = (initial as i64) + 1;
-        // SQLite UPSERT syntax
-        let upsert_sql = r#"
-            INSERT INTO nonces (network, address, next_nonce, updated_at)
-            VALUES (?1, ?2, ?3, ?4)
-            ON CONFLICT(network, address) DO UPDATE SET next_nonce = next_nonce + 1, updated_at = excluded.updated_at
-        "#;
-        sqlx::query(upsert_sql)
-            .bind(network)
-            .bind(address)
-            .bind(seed)
-            .bind(now)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| anyhow::anyhow!("upsert nonce failed: {}", e))?;
+        self.under_nonce_lease(network, address, || self.reserve_nonce_unleased(network, address, initial)).await
+    }
 
-        // Read back the stored next_nonce
-        let row = sqlx::query("SELECT next_nonce FROM nonces WHERE network = ?1 AND address = ?2")
-            .bind(network)
-            .bind(address)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| anyhow::anyhow!("select nonce failed: {}", e))?;
-        let next_nonce: i64 = row.get("next_nonce");
-        // reserved is next_nonce - 1
-        Ok((next_nonce - 1) as u64)
+    async fn reserve_nonce_range(&self, network: &str, address: &str, floor: u64, count: u64) -> Result<u64> {
+        self.reserve_nonce_range(network, address, floor, count).await
     }
 
     async fn mark_nonce_used(&self, network: &str, address: &str, nonce: u64) -> Result<()> {
@@ -1071,10 +4904,10 @@ impl WalletStorageTrait for WalletStorage {
         // Try to update only when desired > next_nonce using a conditional update
         let updated = sqlx::query("UPDATE nonces SET next_nonce = ?1, updated_at = ?2 WHERE network = ?3 AND address = ?4 AND next_nonce < ?1")
             .bind(desired)
-            .bind(Utc::now().naive_utc())
+            .bind(self.now().naive_utc())
             .bind(network)
             .bind(address)
-            .execute(&self.pool)
+            .execute(self.writer())
             .await
             .map_err(|e| anyhow::anyhow!("update nonce failed: {}", e))?;
 
@@ -1085,8 +4918,8 @@ impl WalletStorageTrait for WalletStorage {
                 .bind(network)
                 .bind(address)
                 .bind(desired)
-                .bind(Utc::now().naive_utc())
-                .execute(&self.pool)
+                .bind(self.now().naive_utc())
+                .execute(self.writer())
                 .await
                 .map_err(|e| anyhow::anyhow!("insert/replace nonce failed: {}", e))?;
         }
@@ -1137,7 +4970,7 @@ mod tests {
         
         let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
 
-        // First create a wallet to satisfy foreign key constraint
+        // Store a wallet so the record belongs to a real one
         let wallet_data = b"test wallet data";
         storage.store_wallet("test-wallet", wallet_data, false).await.unwrap();
 
@@ -1158,6 +4991,8 @@ mod tests {
             created_at: Utc::now(),
             confirmed_at: None,
             integrity_hash: String::new(), // Will be calculated during storage
+            fee_actual: None,
+            block_number: None,
         };
 
         // Store transaction (integrity hash will be calculated and stored)
@@ -1204,6 +5039,10 @@ mod tests {
             updated_at: Utc::now(),
             fee_amount: Some("1.0".to_string()),
             estimated_completion_time: Some(Utc::now() + chrono::Duration::hours(1)),
+            amount_minimal_source: None,
+            amount_minimal_dest: None,
+            token_address_source: None,
+            token_address_dest: None,
         };
 
         // Store transaction
@@ -1268,4 +5107,542 @@ mod tests {
         assert_eq!(lbl.current_version, 2);
         assert_eq!(lbl.current_id.as_deref(), Some("key-uuid-v2"));
     }
+
+    async fn seed_triage_transactions(storage: &WalletStorage) -> Vec<String> {
+        let mut ids = Vec::new();
+        for name in ["triage-a", "triage-b", "triage-c"] {
+            storage.store_wallet(name, b"blob", false).await.unwrap();
+        }
+        let wallets = storage.list_wallets().await.unwrap();
+        let now = Utc::now();
+        let seeds = [
+            ("triage-a", "eth", "pending", "1.5", 3600),
+            ("triage-a", "eth", "confirmed", "2.0", 3600),
+            ("triage-b", "polygon", "pending", "0.1", 60),
+            ("triage-b", "eth", "failed", "5.0", 7200),
+            ("triage-c", "eth", "pending", "10.0", 30),
+        ];
+        for (i, (wallet, network, status, amount, age_secs)) in seeds.iter().enumerate() {
+            let wallet_id = wallets.iter().find(|w| w.name == *wallet).unwrap().id.clone();
+            let tx = TransactionRecord {
+                id: format!("triage-tx-{}", i),
+                wallet_id: wallet_id.clone(),
+                tx_hash: format!("0x{:064x}", i),
+                network: network.to_string(),
+                from_address: "0x1234567890123456789012345678901234567890".to_string(),
+                to_address: "0x0987654321098765432109876543210987654321".to_string(),
+                amount: amount.to_string(),
+                fee: "0.001".to_string(),
+                status: status.to_string(),
+                created_at: now - chrono::Duration::seconds(*age_secs),
+                confirmed_at: None,
+                integrity_hash: String::new(),
+                fee_actual: None,
+                block_number: None,
+            };
+            storage.store_transaction(&tx).await.unwrap();
+            ids.push(wallet_id);
+        }
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_query_transactions_filters() {
+        let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
+        let wallet_ids = seed_triage_transactions(&storage).await;
+
+        let (all, total) = storage.query_transactions(&TransactionFilter::default(), 0, 100).await.unwrap();
+        assert_eq!(total, 5);
+        assert_eq!(all.len(), 5);
+
+        let pending = TransactionFilter::default().with_status("pending");
+        let (rows, total) = storage.query_transactions(&pending, 0, 100).await.unwrap();
+        assert_eq!(total, 3);
+        assert!(rows.iter().all(|t| t.status == "pending"));
+        // oldest first
+        assert_eq!(rows[0].id, "triage-tx-0");
+
+        let stale = pending.clone().older_than(chrono::Duration::seconds(600));
+        let (rows, _) = storage.query_transactions(&stale, 0, 100).await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].id, "triage-tx-0");
+
+        let eth_pending = pending.clone().with_network("eth");
+        let (_, total) = storage.query_transactions(&eth_pending, 0, 100).await.unwrap();
+        assert_eq!(total, 2);
+
+        let by_wallet = TransactionFilter { wallet_ids: vec![wallet_ids[2].clone()], ..Default::default() };
+        let (rows, _) = storage.query_transactions(&by_wallet, 0, 100).await.unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let large = TransactionFilter { min_amount: Some(2.0), ..Default::default() };
+        let (_, total) = storage.query_transactions(&large, 0, 100).await.unwrap();
+        assert_eq!(total, 3);
+
+        // pagination keeps the total
+        let (page, total) = storage.query_transactions(&pending, 1, 1).await.unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(page.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_query_uses_indexes() {
+        let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
+        seed_triage_transactions(&storage).await;
+
+        let filter = TransactionFilter::default()
+            .with_status("pending")
+            .older_than(chrono::Duration::seconds(600));
+        let plan = storage.explain_transaction_query(&filter).await.unwrap();
+        assert!(
+            plan.iter().any(|l| l.contains("idx_transactions_status_created")
+                || l.contains("idx_transactions_network_status")),
+            "unexpected plan: {:?}",
+            plan
+        );
+        assert!(!plan.iter().any(|l| l.trim() == "SCAN transactions"), "full scan: {:?}", plan);
+    }
+
+    #[tokio::test]
+    async fn test_wallet_set_transactions_merge_and_paginate() {
+        let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
+        let ids = seed_triage_transactions(&storage).await;
+        // triage-a 与 triage-b；triage-c 不在集合中
+        let set = vec![ids[0].clone(), ids[2].clone()];
+
+        let mut seen = Vec::new();
+        let mut cursor: Option<TransactionCursor> = None;
+        loop {
+            let (rows, next) = storage.wallet_set_transactions(&set, cursor.as_ref(), 2).await.unwrap();
+            assert!(rows.len() <= 2);
+            seen.extend(rows.into_iter().map(|t| t.id));
+            cursor = next.map(|c| TransactionCursor::decode(&c.encode()).unwrap());
+            if cursor.is_none() {
+                break;
+            }
+        }
+        // 同一时刻的两笔按 id 倒序，跨页不重复不遗漏
+        assert_eq!(seen, vec!["triage-tx-2", "triage-tx-1", "triage-tx-0", "triage-tx-3"]);
+
+        let (rows, next) = storage.wallet_set_transactions(&[], None, 10).await.unwrap();
+        assert!(rows.is_empty() && next.is_none());
+        assert!(TransactionCursor::decode("not-a-cursor").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_transaction_status_reseals_integrity() {
+        let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
+        seed_triage_transactions(&storage).await;
+
+        storage
+            .update_transaction_status("triage-tx-0", "confirmed", Some(Utc::now()))
+            .await
+            .unwrap();
+        let filter = TransactionFilter::default().with_status("confirmed");
+        let (rows, total) = storage.query_transactions(&filter, 0, 10).await.unwrap();
+        assert_eq!(total, 2);
+        assert!(rows.iter().any(|t| t.id == "triage-tx-0" && t.confirmed_at.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_list_wallets_keyset_pagination_visits_each_once() {
+        let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
+        for i in 0..5_000 {
+            storage.store_wallet(&format!("page-wallet-{:05}", i), b"blob", false).await.unwrap();
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let mut keys = Vec::new();
+        let mut cursor: Option<WalletCursor> = None;
+        loop {
+            let page = storage.list_wallets_page(cursor.as_ref(), 337).await.unwrap();
+            assert!(page.wallets.len() <= 337);
+            for w in &page.wallets {
+                assert!(seen.insert(w.id.clone()), "wallet visited twice: {}", w.name);
+                keys.push((w.created_at, w.id.clone()));
+            }
+            // round-trip through the opaque API token
+            cursor = page.next_cursor.map(|c| WalletCursor::decode(&c.encode()).unwrap());
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), 5_000);
+        assert!(keys.windows(2).all(|w| w[0] > w[1]), "pages must be strictly newest-first");
+
+        // the unpaginated API still returns everything in the same order
+        let all = storage.list_wallets().await.unwrap();
+        assert_eq!(all.len(), 5_000);
+        assert!(all.iter().zip(keys.iter()).all(|(w, k)| w.id == k.1));
+    }
+
+    #[test]
+    fn test_wallet_page_sql_has_no_offset() {
+        for sql in [wallet_page::FIRST_PAGE_SQL, wallet_page::NEXT_PAGE_SQL] {
+            assert!(!sql.to_uppercase().contains("OFFSET"), "{}", sql);
+        }
+        assert!(WalletCursor::decode("not-a-cursor").is_err());
+    }
+
+    fn balance_snapshot(balance: &str, captured_at: i64) -> BalanceSnapshot {
+        BalanceSnapshot {
+            wallet_id: "42".into(),
+            network: "eth".into(),
+            token: NATIVE_TOKEN.into(),
+            balance: balance.into(),
+            block_number: Some(captured_at / 12),
+            captured_at,
+        }
+    }
+
+    async fn count_balance_snapshots(storage: &WalletStorage) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM balance_snapshots")
+            .fetch_one(&storage.pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_balance_snapshot_skips_unchanged() {
+        let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
+
+        assert!(storage.record_balance_snapshot(&balance_snapshot("1.5", 3_600)).await.unwrap());
+        assert!(!storage.record_balance_snapshot(&balance_snapshot("1.5", 7_200)).await.unwrap());
+        assert_eq!(count_balance_snapshots(&storage).await, 1);
+
+        assert!(storage.record_balance_snapshot(&balance_snapshot("2.0", 10_800)).await.unwrap());
+        // same value as an older row but different from the latest one
+        assert!(storage.record_balance_snapshot(&balance_snapshot("1.5", 14_400)).await.unwrap());
+        assert_eq!(count_balance_snapshots(&storage).await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_balance_history_fills_gaps() {
+        let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
+        for (balance, at) in [("1", 3_600 + 5), ("2", 4 * 3_600 + 5)] {
+            storage.record_balance_snapshot(&balance_snapshot(balance, at)).await.unwrap();
+        }
+
+        let from = chrono::TimeZone::timestamp_opt(&Utc, 2 * 3_600 + 30, 0).unwrap();
+        let to = chrono::TimeZone::timestamp_opt(&Utc, 6 * 3_600, 0).unwrap();
+        let points =
+            storage.balance_history("42", "eth", NATIVE_TOKEN, from, to, SnapshotResolution::Hour).await.unwrap();
+
+        let got: Vec<_> =
+            points.iter().map(|p| (p.timestamp.timestamp(), p.balance.as_str(), p.interpolated)).collect();
+        assert_eq!(
+            got,
+            vec![
+                (2 * 3_600, "1", true),
+                (3 * 3_600, "1", true),
+                (4 * 3_600, "2", false),
+                (5 * 3_600, "2", true),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_downsample_keeps_last_snapshot_per_day() {
+        let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
+        let day = 86_400;
+        for (balance, at) in [
+            ("1", 3_600),
+            ("2", 10 * 3_600),
+            ("3", day - 1), // last of day 0
+            ("4", day + 3_600),
+            ("5", day + 20 * 3_600), // last of day 1
+            ("6", 3 * day + 3_600),  // inside the hourly window
+            ("7", 3 * day + 2 * 3_600),
+        ] {
+            assert!(storage.record_balance_snapshot(&balance_snapshot(balance, at)).await.unwrap());
+        }
+
+        let cutoff = chrono::TimeZone::timestamp_opt(&Utc, 3 * day, 0).unwrap();
+        assert_eq!(storage.downsample_balance_snapshots(cutoff).await.unwrap(), 3);
+        // idempotent
+        assert_eq!(storage.downsample_balance_snapshots(cutoff).await.unwrap(), 0);
+
+        let rows = balance_snapshots::range(&storage.pool, "42", "eth", NATIVE_TOKEN, 0, 4 * day).await.unwrap();
+        let kept: Vec<_> = rows.iter().map(|r| (r.balance.as_str(), r.captured_at)).collect();
+        assert_eq!(
+            kept,
+            vec![("3", day - 1), ("5", day + 20 * 3_600), ("6", 3 * day + 3_600), ("7", 3 * day + 2 * 3_600)]
+        );
+
+        // the daily series still reports the end-of-day balances
+        let from = chrono::TimeZone::timestamp_opt(&Utc, 0, 0).unwrap();
+        let to = chrono::TimeZone::timestamp_opt(&Utc, 3 * day, 0).unwrap();
+        let daily =
+            storage.balance_history("42", "eth", NATIVE_TOKEN, from, to, SnapshotResolution::Day).await.unwrap();
+        let balances: Vec<_> = daily.iter().map(|p| (p.balance.as_str(), p.interpolated)).collect();
+        assert_eq!(balances, vec![("3", false), ("5", false), ("5", true)]);
+    }
+
+    #[tokio::test]
+    async fn test_instances_opening_one_file_together_all_start() {
+        std::env::set_var("WALLET_ENC_KEY", "MTIzNDU2Nzg5MDEyMzQ1Njc4OTAxMjM0NTY3ODkwMTI=");
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("shared.db").display());
+        let opens = (0..4).map(|_| WalletStorage::new_with_url(&url));
+        for opened in futures::future::join_all(opens).await {
+            opened.unwrap();
+        }
+    }
+
+    #[test]
+    fn test_only_lock_contention_is_retried() {
+        assert!(is_lock_contention(&anyhow::anyhow!("Failed to add column: database is locked")));
+        assert!(is_lock_contention(&anyhow::anyhow!("database table is locked").context("schema")));
+        assert!(!is_lock_contention(&anyhow::anyhow!("Failed to add column: duplicate column name: memo")));
+        assert!(!is_lock_contention(&anyhow::anyhow!("no such table: wallets")));
+    }
+
+    #[tokio::test]
+    async fn test_transactions_keyed_by_wallet_id_migration() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("v17.db").display());
+        {
+            // versions 16 and 17 dropped the foreign key and keyed some rows by wallet name
+            let pool = SqlitePool::connect(&url).await.unwrap();
+            sqlx::query(
+                "CREATE TABLE wallets (id TEXT PRIMARY KEY, name TEXT UNIQUE NOT NULL, encrypted_data BLOB NOT NULL, \
+                 quantum_safe BOOLEAN NOT NULL, created_at DATETIME NOT NULL, updated_at DATETIME NOT NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "CREATE TABLE transactions (id TEXT PRIMARY KEY, wallet_id TEXT NOT NULL, tx_hash TEXT NOT NULL, \
+                 network TEXT NOT NULL, from_address TEXT NOT NULL, to_address TEXT NOT NULL, amount TEXT NOT NULL, \
+                 fee TEXT NOT NULL, status TEXT NOT NULL, created_at DATETIME NOT NULL, confirmed_at DATETIME, \
+                 integrity_hash TEXT NOT NULL, fee_actual TEXT, block_number INTEGER)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("INSERT INTO wallets VALUES ('w-id', 'hot', x'00', 0, 0, 0)").execute(&pool).await.unwrap();
+            for (id, wallet_id) in [("by-name", "hot"), ("by-id", "w-id"), ("gone", "deleted")] {
+                let mut record = TransactionRecord {
+                    id: id.to_string(),
+                    wallet_id: wallet_id.to_string(),
+                    tx_hash: "0x01".to_string(),
+                    network: "eth".to_string(),
+                    from_address: "a".to_string(),
+                    to_address: "b".to_string(),
+                    amount: "1".to_string(),
+                    fee: "0".to_string(),
+                    status: "confirmed".to_string(),
+                    created_at: Utc::now(),
+                    confirmed_at: None,
+                    integrity_hash: String::new(),
+                    fee_actual: None,
+                    block_number: None,
+                };
+                record.integrity_hash = WalletStorage::calculate_transaction_integrity_hash(&record);
+                sqlx::query(
+                    "INSERT INTO transactions VALUES (?1, ?2, '0x01', 'eth', 'a', 'b', '1', '0', 'confirmed', ?3, NULL, ?4, \
+                     NULL, NULL)",
+                )
+                .bind(&record.id)
+                .bind(&record.wallet_id)
+                .bind(record.created_at)
+                .bind(&record.integrity_hash)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+            pool.close().await;
+        }
+
+        let storage = WalletStorage::new_with_url(&url).await.unwrap();
+        let fks: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_foreign_key_list('transactions')")
+            .fetch_one(&storage.pool)
+            .await
+            .unwrap();
+        assert_eq!(fks, 1);
+        // name-keyed rows now carry the wallet's id; the row of a deleted wallet is set aside
+        let kept: Vec<(String, String)> = sqlx::query_as("SELECT id, wallet_id FROM transactions ORDER BY id")
+            .fetch_all(&storage.pool)
+            .await
+            .unwrap();
+        assert_eq!(kept, vec![("by-id".to_string(), "w-id".to_string()), ("by-name".to_string(), "w-id".to_string())]);
+        let orphaned: Vec<(String, String)> = sqlx::query_as("SELECT id, wallet_id FROM orphaned_transactions")
+            .fetch_all(&storage.pool)
+            .await
+            .unwrap();
+        assert_eq!(orphaned, vec![("gone".to_string(), "deleted".to_string())]);
+        // the migration is recorded, and the indexes dropped with the old table are back
+        assert_eq!(storage.schema_version().await.unwrap(), SCHEMA_VERSION);
+        let indexes: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'idx_transactions_wallet_id'",
+        )
+        .fetch_one(&storage.pool)
+        .await
+        .unwrap();
+        assert_eq!(indexes, 1);
+
+        let mut record = TransactionRecord {
+            id: "new".to_string(),
+            wallet_id: "w-id".to_string(),
+            tx_hash: "0x02".to_string(),
+            network: "eth".to_string(),
+            from_address: "a".to_string(),
+            to_address: "b".to_string(),
+            amount: "1".to_string(),
+            fee: "0".to_string(),
+            status: "pending".to_string(),
+            created_at: Utc::now(),
+            confirmed_at: None,
+            integrity_hash: String::new(),
+            fee_actual: None,
+            block_number: None,
+        };
+        storage.store_transaction(&record).await.unwrap();
+        // the rewritten row was re-hashed, so it still passes the integrity check
+        assert_eq!(storage.get_wallet_transactions("w-id").await.unwrap().len(), 3);
+        // the constraint holds again
+        record.id = "named".to_string();
+        record.wallet_id = "hot".to_string();
+        assert!(storage.store_transaction(&record).await.is_err());
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_upgrade_rehashes_name_keyed_rows_that_verified() {
+        std::env::set_var("WALLET_ENC_KEY", "MTIzNDU2Nzg5MDEyMzQ1Njc4OTAxMjM0NTY3ODkwMTI=");
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("v17.db").display());
+        let wallet_id = {
+            // a full schema from before version 18, with sends recorded under the wallet's name
+            let storage = WalletStorage::new_with_url(&url).await.unwrap();
+            storage.store_wallet("hot", b"blob", false).await.unwrap();
+            let mut conn = storage.pool.acquire().await.unwrap();
+            sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await.unwrap();
+            for (id, tx_hash, tampered) in [("intact", "0xaa", false), ("tampered", "0xbb", true)] {
+                let mut record = TransactionRecord {
+                    id: id.to_string(),
+                    wallet_id: "hot".to_string(),
+                    tx_hash: tx_hash.to_string(),
+                    network: "eth".to_string(),
+                    from_address: "a".to_string(),
+                    to_address: "b".to_string(),
+                    amount: "1".to_string(),
+                    fee: "0".to_string(),
+                    status: "confirmed".to_string(),
+                    created_at: Utc::now(),
+                    confirmed_at: None,
+                    integrity_hash: String::new(),
+                    fee_actual: None,
+                    block_number: None,
+                };
+                record.integrity_hash = WalletStorage::calculate_transaction_integrity_hash(&record);
+                if tampered {
+                    record.amount = "100".to_string();
+                }
+                sqlx::query(
+                    "INSERT INTO transactions (id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, \
+                     status, created_at, integrity_hash) VALUES (?1, ?2, ?3, 'eth', 'a', 'b', ?4, '0', 'confirmed', ?5, ?6)",
+                )
+                .bind(&record.id)
+                .bind(&record.wallet_id)
+                .bind(&record.tx_hash)
+                .bind(&record.amount)
+                .bind(record.created_at)
+                .bind(&record.integrity_hash)
+                .execute(&mut *conn)
+                .await
+                .unwrap();
+            }
+            sqlx::query("DELETE FROM schema_migrations WHERE version >= ?1")
+                .bind(schema_migrations::TRANSACTIONS_KEYED_BY_WALLET_ID)
+                .execute(&mut *conn)
+                .await
+                .unwrap();
+            drop(conn);
+            let wallet_id = storage.wallet_id("hot").await.unwrap().unwrap();
+            storage.pool.close().await;
+            wallet_id
+        };
+
+        let storage = WalletStorage::new_with_url(&url).await.unwrap();
+        // the row that verified under its name verifies under the wallet's id
+        let intact = storage.transaction_by_hash("0xaa").await.unwrap().unwrap();
+        assert_eq!(intact.wallet_id, wallet_id);
+        // re-keying does not launder a row that had already been altered
+        assert!(storage.transaction_by_hash("0xbb").await.is_err());
+        let violations: Vec<(String, i64)> = sqlx::query_as("SELECT \"table\", rowid FROM pragma_foreign_key_check")
+            .fetch_all(&storage.pool)
+            .await
+            .unwrap();
+        assert!(violations.is_empty());
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_token_credentials_migration_seals_secrets() {
+        std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("v16.db").display());
+        {
+            // version 16 kept the mode and the plaintext secret on the token row
+            let pool = SqlitePool::connect(&url).await.unwrap();
+            sqlx::query(
+                "CREATE TABLE wallet_tokens (id TEXT PRIMARY KEY, token_hash TEXT NOT NULL UNIQUE, \
+                 wallet_id INTEGER NOT NULL, wallet_name TEXT NOT NULL, owner_user_id TEXT NOT NULL, \
+                 capabilities INTEGER NOT NULL, amount_cap TEXT, expires_at INTEGER NOT NULL, \
+                 created_by TEXT NOT NULL, created_at INTEGER NOT NULL, revoked_at INTEGER, \
+                 auth_mode TEXT NOT NULL DEFAULT 'bearer', signing_secret TEXT)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO wallet_tokens VALUES \
+                 ('bearer', 'h1', 1, 'w', 'u', 1, NULL, 9999999999, 'u', 0, NULL, 'bearer', NULL), \
+                 ('hmac', 'h2', 1, 'w', 'u', 1, NULL, 9999999999, 'u', 0, NULL, 'hmac', 'wsk_plain')",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            pool.close().await;
+        }
+
+        let storage = WalletStorage::new_with_url(&url).await.unwrap();
+        let columns: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('wallet_tokens') WHERE name IN ('auth_mode', 'signing_secret')",
+        )
+        .fetch_one(&storage.pool)
+        .await
+        .unwrap();
+        assert_eq!(columns, 0);
+        assert_eq!(storage.find_wallet_token_by_id("bearer").await.unwrap().unwrap().auth_mode, AUTH_MODE_BEARER);
+        let (record, sealed) = storage.find_signing_key("hmac").await.unwrap().unwrap();
+        assert_eq!(record.auth_mode, AUTH_MODE_HMAC);
+        assert_eq!(sealed.open("hmac").unwrap().as_str(), "wsk_plain");
+        assert!(!sealed.ciphertext.windows(9).any(|w| w == b"wsk_plain"));
+        assert_eq!(storage.schema_version().await.unwrap(), SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_open_storage_picks_backend_by_scheme() {
+        let storage = open_storage("sqlite::memory:").await.unwrap();
+        assert!(storage.as_any().downcast_ref::<WalletStorage>().is_some());
+
+        let err = open_storage("mysql://db/wallet").await.err().unwrap();
+        assert_eq!(err.to_string(), "Unsupported storage backend: mysql");
+        // the SQLite store refuses instead of failing on the URL
+        let err = WalletStorage::new_with_url("postgres://db/wallet").await.unwrap_err();
+        assert!(err.to_string().contains("open_storage"), "{}", err);
+        #[cfg(not(feature = "postgres"))]
+        {
+            let err = open_storage("postgresql://db/wallet").await.err().unwrap();
+            assert!(err.to_string().contains("`postgres` feature"), "{}", err);
+        }
+        // with the feature the URL reaches the PostgreSQL driver; `.invalid` never resolves
+        #[cfg(feature = "postgres")]
+        {
+            let err = open_storage("postgresql://wallet.invalid/wallet").await.err().unwrap();
+            assert!(err.to_string().contains("Failed to connect to database"), "{}", err);
+        }
+    }
 }
@@ -0,0 +1,98 @@
+//! Per wallet+network tiered multisig policies.
+//!
+//! Tiers and signer public keys are stored as JSON; the policy is validated
+//! before it is written, and again when it is turned back into a
+//! [`TieredThresholdPolicy`].
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, Row};
+
+use crate::crypto::multisig::{ThresholdTier, TieredThresholdPolicy};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MultisigPolicyRecord {
+    pub wallet_name: String,
+    pub network: String,
+    /// Compressed secp256k1 public keys (hex)
+    pub signers: Vec<String>,
+    /// Ordered by `max_amount`
+    pub tiers: Vec<ThresholdTier>,
+    /// User id of the owner, or `admin`
+    pub updated_by: String,
+    /// Unix seconds
+    pub updated_at: i64,
+}
+
+impl MultisigPolicyRecord {
+    pub fn policy(&self) -> Result<TieredThresholdPolicy> {
+        TieredThresholdPolicy::new(self.tiers.clone(), self.signers.len() as u8)
+    }
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS multisig_policies (
+            wallet_name TEXT NOT NULL,
+            network TEXT NOT NULL,
+            signers TEXT NOT NULL,
+            tiers TEXT NOT NULL,
+            updated_by TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (wallet_name, network)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn upsert(pool: &SqlitePool, record: &MultisigPolicyRecord) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO multisig_policies (wallet_name, network, signers, tiers, updated_by, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ON CONFLICT(wallet_name, network) DO UPDATE SET
+            signers = excluded.signers,
+            tiers = excluded.tiers,
+            updated_by = excluded.updated_by,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&record.wallet_name)
+    .bind(&record.network)
+    .bind(serde_json::to_string(&record.signers)?)
+    .bind(serde_json::to_string(&record.tiers)?)
+    .bind(&record.updated_by)
+    .bind(record.updated_at)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store multisig policy: {}", e))?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, wallet_name: &str, network: &str) -> Result<Option<MultisigPolicyRecord>> {
+    let row = sqlx::query(
+        "SELECT signers, tiers, updated_by, updated_at FROM multisig_policies \
+         WHERE wallet_name = ?1 AND network = ?2",
+    )
+    .bind(wallet_name)
+    .bind(network)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load multisig policy: {}", e))?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    Ok(Some(MultisigPolicyRecord {
+        wallet_name: wallet_name.to_string(),
+        network: network.to_string(),
+        signers: serde_json::from_str(&row.get::<String, _>("signers"))?,
+        tiers: serde_json::from_str(&row.get::<String, _>("tiers"))?,
+        updated_by: row.get("updated_by"),
+        updated_at: row.get("updated_at"),
+    }))
+}
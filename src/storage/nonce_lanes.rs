@@ -0,0 +1,187 @@
+//! Reserved nonce ranges ("lanes") above an address's ordinary nonces.
+//!
+//! A transaction signed now and broadcast much later needs a nonce that the
+//! sends in between will not take. A lane is a contiguous range
+//! `[start_nonce, end_nonce)` set aside for such transactions: the nonce
+//! store and the signing-intent log refuse ordinary nonces inside it, and
+//! lane nonces are handed out in order from `next_nonce`. The owner of the
+//! lane drops it once none of its nonces is held any more, which gives the
+//! whole range back to ordinary sends.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, Connection, FromRow, SqliteConnection};
+
+/// Lane of time-locked transactions
+pub const FUTURE_LANE: &str = "future";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct NonceLane {
+    pub network: String,
+    /// Lowercase hex
+    pub address: String,
+    pub lane: String,
+    /// First reserved nonce
+    pub start_nonce: i64,
+    /// One past the last reserved nonce
+    pub end_nonce: i64,
+    /// Next nonce the lane hands out; `end_nonce` once exhausted
+    pub next_nonce: i64,
+    pub created_at: i64,
+}
+
+/// Outcome of [`reserve`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaneReservation {
+    Reserved(u64),
+    /// Every nonce of the lane is taken
+    Exhausted(NonceLane),
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS nonce_lanes (
+            network TEXT NOT NULL,
+            address TEXT NOT NULL,
+            lane TEXT NOT NULL,
+            start_nonce INTEGER NOT NULL,
+            end_nonce INTEGER NOT NULL,
+            next_nonce INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (network, address, lane)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+const COLUMNS: &str = "network, address, lane, start_nonce, end_nonce, next_nonce, created_at";
+
+/// Takes the next nonce of `lane`, opening it at `[start, start + size)` when
+/// the address has none. An open lane keeps its range whatever `start` says.
+pub async fn reserve(
+    conn: &mut SqliteConnection,
+    network: &str,
+    address: &str,
+    lane: &str,
+    start: u64,
+    size: u64,
+    now: i64,
+) -> Result<LaneReservation> {
+    let address = address.to_lowercase();
+    let end = start.checked_add(size).filter(|end| *end <= i64::MAX as u64);
+    let end = end.ok_or_else(|| anyhow::anyhow!("nonce lane {}+{} out of range", start, size))?;
+    let mut tx = conn.begin().await?;
+    sqlx::query(
+        r#"
+        INSERT INTO nonce_lanes (network, address, lane, start_nonce, end_nonce, next_nonce, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?4, ?6)
+        ON CONFLICT (network, address, lane) DO NOTHING
+        "#,
+    )
+    .bind(network)
+    .bind(&address)
+    .bind(lane)
+    .bind(start as i64)
+    .bind(end as i64)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+    let reserved: Option<i64> = sqlx::query_scalar(
+        r#"
+        UPDATE nonce_lanes SET next_nonce = next_nonce + 1
+        WHERE network = ?1 AND address = ?2 AND lane = ?3 AND next_nonce < end_nonce
+        RETURNING next_nonce - 1
+        "#,
+    )
+    .bind(network)
+    .bind(&address)
+    .bind(lane)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let outcome = match reserved {
+        Some(nonce) => LaneReservation::Reserved(nonce as u64),
+        None => {
+            let lane = sqlx::query_as(&format!(
+                "SELECT {} FROM nonce_lanes WHERE network = ?1 AND address = ?2 AND lane = ?3",
+                COLUMNS
+            ))
+            .bind(network)
+            .bind(&address)
+            .bind(lane)
+            .fetch_one(&mut *tx)
+            .await?;
+            LaneReservation::Exhausted(lane)
+        }
+    };
+    tx.commit().await?;
+    Ok(outcome)
+}
+
+/// The lane of `address` whose range holds `nonce`, if any.
+pub async fn covering(
+    conn: &mut SqliteConnection,
+    network: &str,
+    address: &str,
+    nonce: u64,
+) -> Result<Option<NonceLane>> {
+    let lane = sqlx::query_as(&format!(
+        "SELECT {} FROM nonce_lanes WHERE network = ?1 AND address = ?2 AND start_nonce <= ?3 AND ?3 < end_nonce",
+        COLUMNS
+    ))
+    .bind(network)
+    .bind(address.to_lowercase())
+    .bind(nonce as i64)
+    .fetch_optional(conn)
+    .await?;
+    Ok(lane)
+}
+
+/// A lane of `address` sharing at least one nonce with `[start, end)`, if any.
+pub async fn overlapping(
+    conn: &mut SqliteConnection,
+    network: &str,
+    address: &str,
+    start: u64,
+    end: u64,
+) -> Result<Option<NonceLane>> {
+    let lane = sqlx::query_as(&format!(
+        "SELECT {} FROM nonce_lanes WHERE network = ?1 AND address = ?2 AND start_nonce < ?4 AND ?3 < end_nonce \
+         ORDER BY start_nonce LIMIT 1",
+        COLUMNS
+    ))
+    .bind(network)
+    .bind(address.to_lowercase())
+    .bind(start as i64)
+    .bind(end as i64)
+    .fetch_optional(conn)
+    .await?;
+    Ok(lane)
+}
+
+pub async fn get(pool: &SqlitePool, network: &str, address: &str, lane: &str) -> Result<Option<NonceLane>> {
+    let lane = sqlx::query_as(&format!(
+        "SELECT {} FROM nonce_lanes WHERE network = ?1 AND address = ?2 AND lane = ?3",
+        COLUMNS
+    ))
+    .bind(network)
+    .bind(address.to_lowercase())
+    .bind(lane)
+    .fetch_optional(pool)
+    .await?;
+    Ok(lane)
+}
+
+/// Gives the range of `lane` back to ordinary sends; `false` if it was not open.
+pub async fn close(conn: &mut SqliteConnection, network: &str, address: &str, lane: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM nonce_lanes WHERE network = ?1 AND address = ?2 AND lane = ?3")
+        .bind(network)
+        .bind(address.to_lowercase())
+        .bind(lane)
+        .execute(conn)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
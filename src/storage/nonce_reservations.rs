@@ -0,0 +1,123 @@
+//! Individual nonces handed out by the `nonces` counter.
+//!
+//! The counter only remembers the next nonce, so a signed transaction the
+//! mempool drops leaves its nonce burned and every later send stuck behind
+//! it. Each reservation is recorded here until the chain's transaction count
+//! passes it (it is then marked confirmed) or it is given back. Nonce
+//! reconciliation compares the unconfirmed ones with the chain to roll the
+//! counter back over dropped transactions.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct NonceReservation {
+    pub network: String,
+    pub address: String,
+    pub nonce: i64,
+    /// Unix seconds
+    pub reserved_at: i64,
+    /// Unix seconds; `None` while the chain has not counted the nonce
+    pub confirmed_at: Option<i64>,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS nonce_reservations (
+            network TEXT NOT NULL,
+            address TEXT NOT NULL,
+            nonce INTEGER NOT NULL,
+            reserved_at INTEGER NOT NULL,
+            confirmed_at INTEGER,
+            PRIMARY KEY (network, address, nonce)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+const COLUMNS: &str = "network, address, nonce, reserved_at, confirmed_at";
+
+/// Records `[start, end)` as reserved; a nonce reserved before (and given
+/// back since) starts over as unconfirmed.
+pub async fn record(
+    conn: &mut SqliteConnection,
+    network: &str,
+    address: &str,
+    start: u64,
+    end: u64,
+    now: i64,
+) -> Result<()> {
+    for nonce in start..end {
+        sqlx::query(
+            r#"
+            INSERT INTO nonce_reservations (network, address, nonce, reserved_at, confirmed_at)
+            VALUES (?1, ?2, ?3, ?4, NULL)
+            ON CONFLICT(network, address, nonce)
+            DO UPDATE SET reserved_at = excluded.reserved_at, confirmed_at = NULL
+            "#,
+        )
+        .bind(network)
+        .bind(address)
+        .bind(nonce as i64)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Forgets the unconfirmed reservations in `[start, end)`.
+pub async fn forget(conn: &mut SqliteConnection, network: &str, address: &str, start: u64, end: u64) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM nonce_reservations \
+         WHERE network = ?1 AND address = ?2 AND ?3 <= nonce AND nonce < ?4 AND confirmed_at IS NULL",
+    )
+    .bind(network)
+    .bind(address)
+    .bind(start as i64)
+    .bind(end as i64)
+    .execute(conn)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Marks every unconfirmed reservation below `count` (the chain's transaction
+/// count) confirmed; returns how many were.
+pub async fn confirm_below(
+    conn: &mut SqliteConnection,
+    network: &str,
+    address: &str,
+    count: u64,
+    now: i64,
+) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE nonce_reservations SET confirmed_at = ?1 \
+         WHERE network = ?2 AND address = ?3 AND nonce < ?4 AND confirmed_at IS NULL",
+    )
+    .bind(now)
+    .bind(network)
+    .bind(address)
+    .bind(count as i64)
+    .execute(conn)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Unconfirmed reservations of `address`, lowest nonce first.
+pub async fn unconfirmed(pool: &SqlitePool, network: &str, address: &str) -> Result<Vec<NonceReservation>> {
+    let rows = sqlx::query_as(&format!(
+        "SELECT {} FROM nonce_reservations \
+         WHERE network = ?1 AND address = ?2 AND confirmed_at IS NULL ORDER BY nonce",
+        COLUMNS
+    ))
+    .bind(network)
+    .bind(address)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
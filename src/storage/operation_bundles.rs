@@ -0,0 +1,326 @@
+//! Multi-step operation bundles and the progress of each step.
+//!
+//! A bundle row moves `pending` -> `running` -> `completed` |
+//! `partially_completed` | `failed`; a `running` bundle found after a restart
+//! is marked `interrupted` and can be claimed again. Each step row moves
+//! `pending` -> `running` -> `completed` | `failed`, or straight to `skipped`.
+//! Step definitions are kept as submitted (placeholders included) and the
+//! resolved action is recorded when the step starts, so the output a later
+//! step consumed can always be traced. All moves are compare-and-set on the
+//! current status.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+pub const BUNDLE_PENDING: &str = "pending";
+pub const BUNDLE_RUNNING: &str = "running";
+pub const BUNDLE_INTERRUPTED: &str = "interrupted";
+pub const BUNDLE_COMPLETED: &str = "completed";
+pub const BUNDLE_PARTIALLY_COMPLETED: &str = "partially_completed";
+pub const BUNDLE_FAILED: &str = "failed";
+
+pub const STEP_PENDING: &str = "pending";
+pub const STEP_RUNNING: &str = "running";
+pub const STEP_COMPLETED: &str = "completed";
+pub const STEP_FAILED: &str = "failed";
+pub const STEP_SKIPPED: &str = "skipped";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BundleRecord {
+    pub id: String,
+    pub wallet_name: String,
+    /// `abort_remaining` | `continue`
+    pub failure_policy: String,
+    pub status: String,
+    /// User id of the requester, or `admin`
+    pub requested_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<String>,
+    /// Unix seconds
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// In submission order
+    pub steps: Vec<BundleStepRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BundleStepRecord {
+    pub position: i64,
+    pub step_id: String,
+    pub kind: String,
+    pub depends_on: Vec<String>,
+    /// The step as submitted, placeholders included
+    pub definition: serde_json::Value,
+    /// The action actually executed, once the step has started
+    pub resolved: Option<serde_json::Value>,
+    pub status: String,
+    pub output: Option<serde_json::Value>,
+    pub error_code: Option<String>,
+    pub error: Option<String>,
+    pub started_at: Option<i64>,
+    pub finished_at: Option<i64>,
+}
+
+#[derive(FromRow)]
+struct BundleRow {
+    id: String,
+    wallet_name: String,
+    failure_policy: String,
+    status: String,
+    requested_by: String,
+    token_id: Option<String>,
+    created_at: i64,
+    updated_at: i64,
+}
+
+#[derive(FromRow)]
+struct StepRow {
+    position: i64,
+    step_id: String,
+    kind: String,
+    depends_on: String,
+    definition: String,
+    resolved: Option<String>,
+    status: String,
+    output: Option<String>,
+    error_code: Option<String>,
+    error: Option<String>,
+    started_at: Option<i64>,
+    finished_at: Option<i64>,
+}
+
+impl TryFrom<StepRow> for BundleStepRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(row: StepRow) -> Result<Self> {
+        let json = |text: Option<String>| -> Result<Option<serde_json::Value>> {
+            Ok(text.map(|t| serde_json::from_str(&t)).transpose()?)
+        };
+        Ok(Self {
+            depends_on: serde_json::from_str(&row.depends_on)?,
+            definition: serde_json::from_str(&row.definition)?,
+            resolved: json(row.resolved)?,
+            output: json(row.output)?,
+            position: row.position,
+            step_id: row.step_id,
+            kind: row.kind,
+            status: row.status,
+            error_code: row.error_code,
+            error: row.error,
+            started_at: row.started_at,
+            finished_at: row.finished_at,
+        })
+    }
+}
+
+/// Fields of a new bundle
+#[derive(Debug, Clone)]
+pub struct NewBundle<'a> {
+    pub wallet_name: &'a str,
+    pub failure_policy: &'a str,
+    pub requested_by: &'a str,
+    pub token_id: Option<&'a str>,
+    pub steps: Vec<NewBundleStep<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewBundleStep<'a> {
+    pub step_id: &'a str,
+    pub kind: &'a str,
+    pub depends_on: &'a [String],
+    pub definition: serde_json::Value,
+}
+
+/// Fields set by a step status change; `None` leaves the column as is.
+#[derive(Debug, Clone, Default)]
+pub struct StepProgress<'a> {
+    pub resolved: Option<&'a serde_json::Value>,
+    pub output: Option<&'a serde_json::Value>,
+    pub error_code: Option<&'a str>,
+    pub error: Option<&'a str>,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS operation_bundles (
+            id TEXT PRIMARY KEY,
+            wallet_name TEXT NOT NULL,
+            failure_policy TEXT NOT NULL,
+            status TEXT NOT NULL,
+            requested_by TEXT NOT NULL,
+            token_id TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_operation_bundles_status ON operation_bundles (status)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS operation_bundle_steps (
+            bundle_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            step_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            depends_on TEXT NOT NULL,
+            definition TEXT NOT NULL,
+            resolved TEXT,
+            status TEXT NOT NULL,
+            output TEXT,
+            error_code TEXT,
+            error TEXT,
+            started_at INTEGER,
+            finished_at INTEGER,
+            PRIMARY KEY (bundle_id, position),
+            UNIQUE (bundle_id, step_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn insert(conn: &mut SqliteConnection, id: &str, bundle: &NewBundle<'_>, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO operation_bundles
+            (id, wallet_name, failure_policy, status, requested_by, token_id, created_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+        "#,
+    )
+    .bind(id)
+    .bind(bundle.wallet_name)
+    .bind(bundle.failure_policy)
+    .bind(BUNDLE_PENDING)
+    .bind(bundle.requested_by)
+    .bind(bundle.token_id)
+    .bind(now)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store bundle: {}", e))?;
+
+    for (position, step) in bundle.steps.iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO operation_bundle_steps (bundle_id, position, step_id, kind, depends_on, definition, status)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(id)
+        .bind(position as i64)
+        .bind(step.step_id)
+        .bind(step.kind)
+        .bind(serde_json::to_string(step.depends_on)?)
+        .bind(step.definition.to_string())
+        .bind(STEP_PENDING)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to store bundle step: {}", e))?;
+    }
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, id: &str) -> Result<Option<BundleRecord>> {
+    let row = sqlx::query_as::<_, BundleRow>(
+        "SELECT id, wallet_name, failure_policy, status, requested_by, token_id, created_at, updated_at \
+         FROM operation_bundles WHERE id = ?1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load bundle: {}", e))?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let steps = sqlx::query_as::<_, StepRow>(
+        "SELECT position, step_id, kind, depends_on, definition, resolved, status, output, error_code, error, \
+         started_at, finished_at FROM operation_bundle_steps WHERE bundle_id = ?1 ORDER BY position",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load bundle steps: {}", e))?
+    .into_iter()
+    .map(BundleStepRecord::try_from)
+    .collect::<Result<Vec<_>>>()?;
+    Ok(Some(BundleRecord {
+        id: row.id,
+        wallet_name: row.wallet_name,
+        failure_policy: row.failure_policy,
+        status: row.status,
+        requested_by: row.requested_by,
+        token_id: row.token_id,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        steps,
+    }))
+}
+
+pub async fn transition(conn: &mut SqliteConnection, id: &str, from: &str, to: &str, now: i64) -> Result<bool> {
+    let result = sqlx::query("UPDATE operation_bundles SET status = ?3, updated_at = ?4 WHERE id = ?1 AND status = ?2")
+        .bind(id)
+        .bind(from)
+        .bind(to)
+        .bind(now)
+        .execute(conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update bundle: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Entering `running` stamps `started_at`; any other target stamps
+/// `finished_at`.
+pub async fn transition_step(
+    conn: &mut SqliteConnection,
+    bundle_id: &str,
+    position: i64,
+    from: &str,
+    to: &str,
+    progress: &StepProgress<'_>,
+    now: i64,
+) -> Result<bool> {
+    let started = to == STEP_RUNNING;
+    let result = sqlx::query(
+        r#"
+        UPDATE operation_bundle_steps
+        SET status = ?4,
+            resolved = COALESCE(?5, resolved),
+            output = COALESCE(?6, output),
+            error_code = COALESCE(?7, error_code),
+            error = COALESCE(?8, error),
+            started_at = CASE WHEN ?9 THEN ?10 ELSE started_at END,
+            finished_at = CASE WHEN ?9 THEN finished_at ELSE ?10 END
+        WHERE bundle_id = ?1 AND position = ?2 AND status = ?3
+        "#,
+    )
+    .bind(bundle_id)
+    .bind(position)
+    .bind(from)
+    .bind(to)
+    .bind(progress.resolved.map(|v| v.to_string()))
+    .bind(progress.output.map(|v| v.to_string()))
+    .bind(progress.error_code)
+    .bind(progress.error)
+    .bind(started)
+    .bind(now)
+    .execute(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to update bundle step: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn ids_with_status(pool: &SqlitePool, status: &str) -> Result<Vec<String>> {
+    sqlx::query_scalar("SELECT id FROM operation_bundles WHERE status = ?1 ORDER BY created_at, id")
+        .bind(status)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list bundles: {}", e))
+}
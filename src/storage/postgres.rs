@@ -0,0 +1,742 @@
+//! PostgreSQL implementation of [`WalletStorageTrait`].
+//!
+//! Covers the tables the trait reaches: wallets, transactions (with the same
+//! integrity hash as the SQLite store), audit logs with their HMAC chain,
+//! nonces, key rotation and bridge transactions. Everything else the server
+//! keeps (journal, nonce lanes, approvals, ...) is still SQLite-only, so
+//! [`WalletServer`](crate::api::server::WalletServer) keeps requiring a
+//! `sqlite:` URL; this backend serves callers that only need the trait.
+//!
+//! Several instances may share one database. Nonces are reserved with a
+//! single `INSERT ... ON CONFLICT ... RETURNING`, which row-locks the counter,
+//! and audit rows are appended under a transaction-scoped advisory lock so
+//! two writers cannot link to the same previous MAC.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::{FromRow, PgConnection, Row};
+use tracing::{debug, info, warn};
+
+use super::audit_chain::{self, AuditChainReport, ChainRow};
+use super::key_rotation::{KeyLabelRecord, KeyVersionRecord};
+use super::wallet_creation::CREATION_COMPLETE;
+use super::{AuditLog, TransactionRecord, WalletMetadata, WalletStorage, WalletStorageTrait};
+use crate::blockchain::bridge::{BridgeTransaction, BridgeTransactionStatus};
+use crate::core::clock::{system_clock, Clock};
+use crate::core::ids::{random_ids, IdGenerator};
+
+/// Advisory lock keys; arbitrary, but fixed across releases
+const SCHEMA_LOCK: i64 = 0x7761_6c6c_6574_0001;
+const AUDIT_CHAIN_LOCK: i64 = 0x7761_6c6c_6574_0002;
+
+const SCHEMA: &[&str] = &[
+    r#"
+    CREATE TABLE IF NOT EXISTS wallets (
+        id TEXT PRIMARY KEY,
+        name TEXT UNIQUE NOT NULL,
+        encrypted_data BYTEA NOT NULL,
+        quantum_safe BOOLEAN NOT NULL,
+        created_at TIMESTAMP NOT NULL,
+        updated_at TIMESTAMP NOT NULL,
+        creation_state TEXT NOT NULL DEFAULT 'complete'
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS transactions (
+        id TEXT PRIMARY KEY,
+        wallet_id TEXT NOT NULL REFERENCES wallets (id),
+        tx_hash TEXT NOT NULL,
+        network TEXT NOT NULL,
+        from_address TEXT NOT NULL,
+        to_address TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        fee TEXT NOT NULL,
+        status TEXT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL,
+        confirmed_at TIMESTAMPTZ,
+        integrity_hash TEXT NOT NULL,
+        fee_actual TEXT,
+        block_number BIGINT
+    )
+    "#,
+    // receipt fields, for tables created before they existed
+    "ALTER TABLE transactions ADD COLUMN IF NOT EXISTS fee_actual TEXT",
+    "ALTER TABLE transactions ADD COLUMN IF NOT EXISTS block_number BIGINT",
+    r#"
+    CREATE TABLE IF NOT EXISTS audit_logs (
+        id BIGSERIAL PRIMARY KEY,
+        wallet_id TEXT,
+        action TEXT NOT NULL,
+        details TEXT,
+        ip_address TEXT,
+        user_agent TEXT,
+        created_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS audit_logs_hmac (
+        audit_id BIGINT PRIMARY KEY REFERENCES audit_logs (id),
+        mac TEXT NOT NULL,
+        prev_mac TEXT
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS bridge_transactions (
+        id TEXT PRIMARY KEY,
+        from_wallet TEXT NOT NULL,
+        from_chain TEXT NOT NULL,
+        to_chain TEXT NOT NULL,
+        token TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        status TEXT NOT NULL,
+        source_tx_hash TEXT,
+        destination_tx_hash TEXT,
+        created_at TIMESTAMPTZ NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL,
+        fee_amount TEXT,
+        estimated_completion_time TIMESTAMPTZ,
+        amount_minimal_source TEXT,
+        amount_minimal_dest TEXT,
+        token_address_source TEXT,
+        token_address_dest TEXT
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS nonces (
+        network TEXT NOT NULL,
+        address TEXT NOT NULL,
+        next_nonce BIGINT NOT NULL,
+        updated_at TIMESTAMP NOT NULL,
+        PRIMARY KEY (network, address)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS key_labels (
+        label TEXT PRIMARY KEY,
+        current_version BIGINT NOT NULL,
+        current_id TEXT
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS key_versions (
+        label TEXT NOT NULL,
+        version BIGINT NOT NULL,
+        key_id TEXT NOT NULL,
+        retired BOOLEAN NOT NULL DEFAULT FALSE,
+        usage_count BIGINT NOT NULL DEFAULT 0,
+        created_at BIGINT NOT NULL,
+        PRIMARY KEY (label, version)
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_transactions_wallet_id ON transactions (wallet_id)",
+    "CREATE INDEX IF NOT EXISTS idx_transactions_tx_hash ON transactions (tx_hash)",
+    "CREATE INDEX IF NOT EXISTS idx_audit_logs_wallet_id ON audit_logs (wallet_id)",
+    "CREATE INDEX IF NOT EXISTS idx_bridge_created_id ON bridge_transactions (created_at, id)",
+    "CREATE INDEX IF NOT EXISTS idx_bridge_status_updated ON bridge_transactions (status, updated_at)",
+];
+
+#[derive(Debug)]
+pub struct PostgresWalletStorage {
+    pool: PgPool,
+    /// Timestamps of written rows; pinned in tests
+    clock: Arc<dyn Clock>,
+    /// Ids of new wallets
+    ids: Arc<dyn IdGenerator>,
+}
+
+impl PostgresWalletStorage {
+    /// Connects to `database_url` (`postgres://` or `postgresql://`) and
+    /// creates the tables that are missing.
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(20)
+            .min_connections(2)
+            .acquire_timeout(std::time::Duration::from_secs(30))
+            .idle_timeout(std::time::Duration::from_secs(600))
+            .max_lifetime(std::time::Duration::from_secs(1800))
+            .connect(database_url)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to database: {}", e))?;
+        Self::with_pool(pool).await
+    }
+
+    /// Uses an existing pool; the schema is created as in [`Self::connect`].
+    pub async fn with_pool(pool: PgPool) -> Result<Self> {
+        let storage = Self { pool, clock: system_clock(), ids: random_ids() };
+        storage.initialize_schema().await?;
+        info!("PostgreSQL wallet storage initialized");
+        Ok(storage)
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        self.clock.now()
+    }
+
+    /// Instances starting together take turns; `CREATE TABLE IF NOT EXISTS`
+    /// alone can still collide on the catalog.
+    async fn initialize_schema(&self) -> Result<()> {
+        debug!("Initializing PostgreSQL schema");
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)").bind(SCHEMA_LOCK).execute(&mut *tx).await?;
+        for statement in SCHEMA {
+            sqlx::query(statement)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to initialize schema: {}", e))?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn store_wallet(&self, name: &str, encrypted_data: &[u8], quantum_safe: bool) -> Result<()> {
+        debug!("Storing wallet: {}", name);
+        let wallet_id = self.ids.new_id();
+        let now = self.now().naive_utc();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO wallets (id, name, encrypted_data, quantum_safe, created_at, updated_at, creation_state)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(&wallet_id)
+        .bind(name)
+        .bind(encrypted_data)
+        .bind(quantum_safe)
+        .bind(now)
+        .bind(now)
+        .bind(CREATION_COMPLETE)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to store wallet: {}", e))?;
+        self.insert_audit(&mut tx, &wallet_id, "wallet_created", &format!("Wallet '{}' created", name), None, None)
+            .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to store wallet: {}", e))?;
+        Ok(())
+    }
+
+    pub async fn load_wallet(&self, name: &str) -> Result<(Vec<u8>, bool)> {
+        debug!("Loading wallet: {}", name);
+        let row =
+            sqlx::query("SELECT id, encrypted_data, quantum_safe FROM wallets WHERE name = $1 AND creation_state = $2")
+                .bind(name)
+                .bind(CREATION_COMPLETE)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load wallet: {}", e))?;
+        let Some(row) = row else {
+            return Err(anyhow::anyhow!("Wallet not found: {}", name));
+        };
+        let wallet_id: String = row.get("id");
+        self.log_action(&wallet_id, "wallet_accessed", &format!("Wallet '{}' accessed", name), None, None).await?;
+        Ok((row.get("encrypted_data"), row.get("quantum_safe")))
+    }
+
+    /// Every wallet, newest first
+    pub async fn list_wallets(&self) -> Result<Vec<WalletMetadata>> {
+        sqlx::query_as::<_, WalletMetadata>(
+            "SELECT id, name, quantum_safe, created_at, updated_at FROM wallets \
+             WHERE creation_state = $1 ORDER BY created_at DESC, id DESC",
+        )
+        .bind(CREATION_COMPLETE)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list wallets: {}", e))
+    }
+
+    pub async fn update_wallet_encrypted_data(&self, name: &str, encrypted_data: &[u8]) -> Result<()> {
+        let result = sqlx::query("UPDATE wallets SET encrypted_data = $1, updated_at = $2 WHERE name = $3")
+            .bind(encrypted_data)
+            .bind(self.now().naive_utc())
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to update wallet: {}", e))?;
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Wallet not found: {}", name));
+        }
+        Ok(())
+    }
+
+    pub async fn delete_wallet(&self, name: &str) -> Result<()> {
+        debug!("Deleting wallet: {}", name);
+        let mut tx = self.pool.begin().await?;
+        let wallet_id: Option<String> = sqlx::query_scalar("DELETE FROM wallets WHERE name = $1 RETURNING id")
+            .bind(name)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to delete wallet: {}", e))?;
+        let Some(wallet_id) = wallet_id else {
+            return Err(anyhow::anyhow!("Wallet not found: {}", name));
+        };
+        self.insert_audit(&mut tx, &wallet_id, "wallet_deleted", &format!("Wallet '{}' deleted", name), None, None)
+            .await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to delete wallet: {}", e))?;
+        warn!("Wallet deleted: {}", name);
+        Ok(())
+    }
+
+    /// Stores `tx_data` with its integrity hash; the hash is the one
+    /// [`WalletStorage`] computes, so rows can move between backends.
+    pub async fn store_transaction(&self, tx_data: &TransactionRecord) -> Result<()> {
+        let integrity_hash = WalletStorage::calculate_transaction_integrity_hash(tx_data);
+        sqlx::query(
+            r#"
+            INSERT INTO transactions (id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status,
+                created_at, confirmed_at, integrity_hash, fee_actual, block_number)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            "#,
+        )
+        .bind(&tx_data.id)
+        .bind(&tx_data.wallet_id)
+        .bind(&tx_data.tx_hash)
+        .bind(&tx_data.network)
+        .bind(&tx_data.from_address)
+        .bind(&tx_data.to_address)
+        .bind(&tx_data.amount)
+        .bind(&tx_data.fee)
+        .bind(&tx_data.status)
+        .bind(tx_data.created_at)
+        .bind(tx_data.confirmed_at)
+        .bind(integrity_hash)
+        .bind(&tx_data.fee_actual)
+        .bind(tx_data.block_number)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to store transaction: {}", e))?;
+        Ok(())
+    }
+
+    /// Transactions of `wallet_id`, newest first; fails on a row whose
+    /// integrity hash does not match.
+    pub async fn get_wallet_transactions(&self, wallet_id: &str) -> Result<Vec<TransactionRecord>> {
+        let transactions = sqlx::query_as::<_, TransactionRecord>(
+            "SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at, \
+             confirmed_at, integrity_hash, fee_actual, block_number FROM transactions WHERE wallet_id = $1 \
+             ORDER BY created_at DESC",
+        )
+        .bind(wallet_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get transactions: {}", e))?;
+        for tx in &transactions {
+            WalletStorage::verify_transaction_integrity(tx)?;
+        }
+        Ok(transactions)
+    }
+
+    pub async fn log_action(
+        &self,
+        wallet_id: &str,
+        action: &str,
+        details: &str,
+        ip_address: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        self.insert_audit(&mut tx, wallet_id, action, details, ip_address, user_agent).await?;
+        tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to log action: {}", e))?;
+        Ok(())
+    }
+
+    /// Audit row plus its chained HMAC inside the caller's transaction. The
+    /// advisory lock is held until that transaction ends, so the row before
+    /// this one is final when its MAC is read.
+    async fn insert_audit(
+        &self,
+        conn: &mut PgConnection,
+        wallet_id: &str,
+        action: &str,
+        details: &str,
+        ip_address: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query("SELECT pg_advisory_xact_lock($1)").bind(AUDIT_CHAIN_LOCK).execute(&mut *conn).await?;
+        let audit_id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO audit_logs (wallet_id, action, details, ip_address, user_agent, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id
+            "#,
+        )
+        .bind(wallet_id)
+        .bind(action)
+        .bind(details)
+        .bind(ip_address)
+        .bind(user_agent)
+        .bind(self.now())
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to log action: {}", e))?;
+        let prev_mac: Option<String> =
+            sqlx::query_scalar("SELECT mac FROM audit_logs_hmac WHERE audit_id < $1 ORDER BY audit_id DESC LIMIT 1")
+                .bind(audit_id)
+                .fetch_optional(&mut *conn)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load previous audit mac: {}", e))?;
+        let mac = WalletStorage::compute_audit_mac(
+            audit_id,
+            wallet_id,
+            action,
+            details,
+            ip_address,
+            user_agent,
+            prev_mac.as_deref(),
+        )?;
+        sqlx::query("INSERT INTO audit_logs_hmac (audit_id, mac, prev_mac) VALUES ($1, $2, $3)")
+            .bind(audit_id)
+            .bind(mac)
+            .bind(prev_mac)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to store audit mac: {}", e))?;
+        Ok(())
+    }
+
+    /// Audit rows, newest first, each checked against its stored MAC; fails
+    /// as well when the chain over the whole log is broken.
+    pub async fn get_audit_logs(&self, wallet_id: Option<&str>) -> Result<Vec<AuditLog>> {
+        let rows = sqlx::query(
+            "SELECT a.*, h.mac, h.prev_mac FROM audit_logs a LEFT JOIN audit_logs_hmac h ON h.audit_id = a.id \
+             WHERE $1::TEXT IS NULL OR a.wallet_id = $1 ORDER BY a.created_at DESC, a.id DESC",
+        )
+        .bind(wallet_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get audit logs: {}", e))?;
+
+        let mut logs = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let log = AuditLog::from_row(row)?;
+            let Some(stored): Option<String> = row.get("mac") else {
+                return Err(anyhow::anyhow!("Audit log integrity failed for id {}: Missing audit mac", log.id));
+            };
+            let prev_mac: Option<String> = row.get("prev_mac");
+            let calc = WalletStorage::compute_audit_mac(
+                log.id,
+                log.wallet_id.as_deref().unwrap_or(""),
+                &log.action,
+                log.details.as_deref().unwrap_or(""),
+                log.ip_address.as_deref(),
+                log.user_agent.as_deref(),
+                prev_mac.as_deref(),
+            )?;
+            if stored != calc {
+                return Err(anyhow::anyhow!("Audit log integrity failed for id {}: MAC mismatch", log.id));
+            }
+            logs.push(log);
+        }
+
+        // Row MACs miss deleted rows; the chain does not
+        let chain = self.verify_audit_chain().await?;
+        if let (Some(id), Some(reason)) = (chain.broken_at, chain.reason) {
+            return Err(anyhow::anyhow!("Audit log chain broken at id {}: {}", id, reason));
+        }
+        Ok(logs)
+    }
+
+    /// Walks the whole audit log in id order, as
+    /// [`WalletStorage::verify_audit_chain`] does, and reports the first entry
+    /// whose MAC or previous-MAC link does not verify.
+    pub async fn verify_audit_chain(&self) -> Result<AuditChainReport> {
+        let rows: Vec<ChainRow> = sqlx::query_as(&format!("{} ORDER BY a.id", audit_chain::CHAIN_SELECT))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load audit chain: {}", e))?;
+        audit_chain::walk(&rows)
+    }
+
+    /// Next nonce that would be handed out for `address`, if any was reserved.
+    pub async fn next_nonce(&self, network: &str, address: &str) -> Result<Option<u64>> {
+        let next: Option<i64> = sqlx::query_scalar("SELECT next_nonce FROM nonces WHERE network = $1 AND address = $2")
+            .bind(network)
+            .bind(address)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(next.map(|n| n as u64))
+    }
+
+    pub async fn store_bridge_transaction(&self, tx: &BridgeTransaction) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO bridge_transactions (id, from_wallet, from_chain, to_chain, token, amount, status,
+                source_tx_hash, destination_tx_hash, created_at, updated_at, fee_amount, estimated_completion_time,
+                amount_minimal_source, amount_minimal_dest, token_address_source, token_address_dest)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+            "#,
+        )
+        .bind(&tx.id)
+        .bind(&tx.from_wallet)
+        .bind(&tx.from_chain)
+        .bind(&tx.to_chain)
+        .bind(&tx.token)
+        .bind(&tx.amount)
+        .bind(serde_json::to_string(&tx.status)?)
+        .bind(&tx.source_tx_hash)
+        .bind(&tx.destination_tx_hash)
+        .bind(tx.created_at)
+        .bind(tx.updated_at)
+        .bind(&tx.fee_amount)
+        .bind(tx.estimated_completion_time)
+        .bind(&tx.amount_minimal_source)
+        .bind(&tx.amount_minimal_dest)
+        .bind(&tx.token_address_source)
+        .bind(&tx.token_address_dest)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_bridge_transaction(&self, id: &str) -> Result<BridgeTransaction> {
+        let row = sqlx::query("SELECT * FROM bridge_transactions WHERE id = $1").bind(id).fetch_one(&self.pool).await?;
+        bridge_from_row(&row)
+    }
+
+    pub async fn update_bridge_transaction_status(
+        &self,
+        id: &str,
+        status: BridgeTransactionStatus,
+        source_tx_hash: Option<String>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE bridge_transactions SET status = $1, updated_at = $2, \
+             source_tx_hash = COALESCE($3, source_tx_hash) WHERE id = $4",
+        )
+        .bind(serde_json::to_string(&status)?)
+        .bind(self.now())
+        .bind(source_tx_hash)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+fn bridge_from_row(row: &PgRow) -> Result<BridgeTransaction> {
+    let status: String = row.get("status");
+    let status = serde_json::from_str(&status).map_err(|e| anyhow::anyhow!("Failed to parse bridge status: {}", e))?;
+    Ok(BridgeTransaction {
+        id: row.get("id"),
+        from_wallet: row.get("from_wallet"),
+        from_chain: row.get("from_chain"),
+        to_chain: row.get("to_chain"),
+        token: row.get("token"),
+        amount: row.get("amount"),
+        status,
+        source_tx_hash: row.get("source_tx_hash"),
+        destination_tx_hash: row.get("destination_tx_hash"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        fee_amount: row.get("fee_amount"),
+        estimated_completion_time: row.get("estimated_completion_time"),
+        amount_minimal_source: row.get("amount_minimal_source"),
+        amount_minimal_dest: row.get("amount_minimal_dest"),
+        token_address_source: row.get("token_address_source"),
+        token_address_dest: row.get("token_address_dest"),
+    })
+}
+
+#[async_trait]
+impl WalletStorageTrait for PostgresWalletStorage {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn store_wallet(&self, name: &str, data: &[u8], quantum_safe: bool) -> Result<()> {
+        self.store_wallet(name, data, quantum_safe).await
+    }
+
+    async fn load_wallet(&self, name: &str) -> Result<(Vec<u8>, bool)> {
+        self.load_wallet(name).await
+    }
+
+    async fn list_wallets(&self) -> Result<Vec<WalletMetadata>> {
+        self.list_wallets().await
+    }
+
+    async fn delete_wallet(&self, name: &str) -> Result<()> {
+        self.delete_wallet(name).await
+    }
+
+    async fn update_wallet_encrypted_data(&self, name: &str, data: &[u8]) -> Result<()> {
+        self.update_wallet_encrypted_data(name, data).await
+    }
+
+    async fn store_bridge_transaction(&self, tx: &BridgeTransaction) -> Result<()> {
+        self.store_bridge_transaction(tx).await
+    }
+
+    async fn get_bridge_transaction(&self, id: &str) -> Result<BridgeTransaction> {
+        self.get_bridge_transaction(id).await
+    }
+
+    async fn update_bridge_transaction_status(
+        &self,
+        id: &str,
+        status: BridgeTransactionStatus,
+        source_tx_hash: Option<String>,
+    ) -> Result<()> {
+        self.update_bridge_transaction_status(id, status, source_tx_hash).await
+    }
+
+    async fn rotation_upsert_label(&self, label: &str, current_version: i64, current_id: Option<&str>) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO key_labels (label, current_version, current_id) VALUES ($1, $2, $3) \
+             ON CONFLICT (label) DO UPDATE SET current_version = EXCLUDED.current_version, \
+             current_id = EXCLUDED.current_id",
+        )
+        .bind(label)
+        .bind(current_version)
+        .bind(current_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn rotation_insert_version(&self, label: &str, version: i64, key_id: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO key_versions (label, version, key_id, retired, usage_count, created_at) \
+             VALUES ($1, $2, $3, FALSE, 0, $4)",
+        )
+        .bind(label)
+        .bind(version)
+        .bind(key_id)
+        .bind(self.now().timestamp())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn rotation_mark_retired(&self, label: &str, version: i64) -> Result<()> {
+        sqlx::query("UPDATE key_versions SET retired = TRUE WHERE label = $1 AND version = $2")
+            .bind(label)
+            .bind(version)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn rotation_inc_usage(&self, label: &str, version: i64) -> Result<()> {
+        sqlx::query("UPDATE key_versions SET usage_count = usage_count + 1 WHERE label = $1 AND version = $2")
+            .bind(label)
+            .bind(version)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn rotation_get_label(&self, label: &str) -> Result<Option<KeyLabelRecord>> {
+        let row = sqlx::query("SELECT label, current_version, current_id FROM key_labels WHERE label = $1")
+            .bind(label)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| KeyLabelRecord {
+            label: r.get("label"),
+            current_version: r.get("current_version"),
+            current_id: r.get("current_id"),
+        }))
+    }
+
+    async fn rotation_get_version(&self, label: &str, version: i64) -> Result<Option<KeyVersionRecord>> {
+        let row = sqlx::query(
+            "SELECT label, version, key_id, retired, usage_count, created_at FROM key_versions \
+             WHERE label = $1 AND version = $2",
+        )
+        .bind(label)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|r| KeyVersionRecord {
+            label: r.get("label"),
+            version: r.get("version"),
+            key_id: r.get("key_id"),
+            retired: r.get("retired"),
+            usage_count: r.get("usage_count"),
+            created_at: r.get("created_at"),
+        }))
+    }
+
+    /// One statement: the conflicting row stays locked until it returns, so
+    /// concurrent reservations on other connections get distinct nonces.
+    async fn reserve_next_nonce(&self, network: &str, address: &str, initial: u64) -> Result<u64> {
+        let next: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO nonces (network, address, next_nonce, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (network, address)
+            DO UPDATE SET next_nonce = nonces.next_nonce + 1, updated_at = EXCLUDED.updated_at
+            RETURNING next_nonce
+            "#,
+        )
+        .bind(network)
+        .bind(address)
+        .bind(initial as i64 + 1)
+        .bind(self.now().naive_utc())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("upsert nonce failed: {}", e))?;
+        Ok((next - 1) as u64)
+    }
+
+    async fn reserve_nonce_range(&self, network: &str, address: &str, floor: u64, count: u64) -> Result<u64> {
+        if count == 0 {
+            return Err(anyhow::anyhow!("cannot reserve an empty nonce range"));
+        }
+        let end: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO nonces (network, address, next_nonce, updated_at)
+            VALUES ($1, $2, $3 + $4, $5)
+            ON CONFLICT (network, address)
+            DO UPDATE SET next_nonce = GREATEST(nonces.next_nonce, $3) + $4, updated_at = EXCLUDED.updated_at
+            RETURNING next_nonce
+            "#,
+        )
+        .bind(network)
+        .bind(address)
+        .bind(floor as i64)
+        .bind(count as i64)
+        .bind(self.now().naive_utc())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("upsert nonce range failed: {}", e))?;
+        Ok(end as u64 - count)
+    }
+
+    async fn mark_nonce_used(&self, network: &str, address: &str, nonce: u64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO nonces (network, address, next_nonce, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (network, address)
+            DO UPDATE SET next_nonce = GREATEST(nonces.next_nonce, EXCLUDED.next_nonce),
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(network)
+        .bind(address)
+        .bind(nonce as i64 + 1)
+        .bind(self.now().naive_utc())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("update nonce failed: {}", e))?;
+        Ok(())
+    }
+}
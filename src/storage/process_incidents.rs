@@ -0,0 +1,108 @@
+//! Panics caught anywhere in the process.
+//!
+//! Rows are written by the incident writer in `ops::incidents` from reports
+//! the panic hook queues, never by the panicking code itself. Messages are
+//! sanitized before they get here; the backtrace is kept only as a short
+//! hash so repeated panics from the same place can be grouped.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct IncidentRecord {
+    pub id: i64,
+    /// Name the task was spawned under (`job:<name>` for job runs), or
+    /// `unnamed` for tasks and threads without one
+    pub task: String,
+    pub message: String,
+    /// `file:line:column` of the panic
+    pub location: Option<String>,
+    /// Hex prefix of the SHA-256 of the captured backtrace
+    pub backtrace_hash: String,
+    pub thread: Option<String>,
+    /// Unix seconds
+    pub occurred_at: i64,
+}
+
+/// Fields of a new `process_incidents` row.
+#[derive(Debug, Clone)]
+pub struct NewIncident<'a> {
+    pub task: &'a str,
+    pub message: &'a str,
+    pub location: Option<&'a str>,
+    pub backtrace_hash: &'a str,
+    pub thread: Option<&'a str>,
+    pub occurred_at: i64,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS process_incidents (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task TEXT NOT NULL,
+            message TEXT NOT NULL,
+            location TEXT,
+            backtrace_hash TEXT NOT NULL,
+            thread TEXT,
+            occurred_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_process_incidents_occurred ON process_incidents (occurred_at)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn insert(pool: &SqlitePool, incident: &NewIncident<'_>) -> Result<i64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO process_incidents (task, message, location, backtrace_hash, thread, occurred_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        "#,
+    )
+    .bind(incident.task)
+    .bind(incident.message)
+    .bind(incident.location)
+    .bind(incident.backtrace_hash)
+    .bind(incident.thread)
+    .bind(incident.occurred_at)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record incident: {}", e))?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Incidents at or after `since`, most recent first.
+pub async fn since(pool: &SqlitePool, since: i64, limit: i64) -> Result<Vec<IncidentRecord>> {
+    sqlx::query_as::<_, IncidentRecord>(
+        r#"
+        SELECT id, task, message, location, backtrace_hash, thread, occurred_at
+        FROM process_incidents
+        WHERE occurred_at >= ?1
+        ORDER BY occurred_at DESC, id DESC
+        LIMIT ?2
+        "#,
+    )
+    .bind(since)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load incidents: {}", e))
+}
+
+/// Number of incidents at or after `since`, regardless of any page limit.
+pub async fn count_since(pool: &SqlitePool, since: i64) -> Result<i64> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM process_incidents WHERE occurred_at >= ?1")
+        .bind(since)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to count incidents: {}", e))
+}
@@ -0,0 +1,380 @@
+//! Per-statement timeouts and timings for storage queries.
+//!
+//! [`run`] checks a connection out of the pool, arms a SQLite progress
+//! handler with the query's deadline and races the query against the same
+//! deadline. SQLite interrupts the running statement once the deadline
+//! passes, so a runaway query stops on the database side too instead of
+//! keeping the connection's worker busy after the caller gave up. A
+//! connection released past its deadline is closed rather than returned to
+//! the pool.
+//!
+//! The budget covers the wait for a connection as well. Running out of it
+//! while waiting is reported as [`QueryError::PoolExhausted`], distinct from
+//! a statement that was interrupted ([`QueryError::Timeout`]).
+//!
+//! Every run is timed into `database_operations_seconds{query}` under its
+//! static name. Runs slower than the slow threshold are logged by name and
+//! class only; bind parameters never reach the log.
+
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use sqlx::pool::PoolConnection;
+use sqlx::sqlite::{Sqlite, SqliteConnection, SqlitePool};
+use tracing::warn;
+
+use crate::monitoring::WalletMetrics;
+
+/// VM instructions between two deadline checks
+const PROGRESS_OPS: i32 = 1000;
+/// `SQLITE_INTERRUPT`
+const SQLITE_INTERRUPT: &str = "9";
+
+const ARMED: u8 = 0;
+const DISARMED: u8 = 1;
+const CANCELLED: u8 = 2;
+
+/// Which timeout a query runs under
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryClass {
+    /// Writes on the send path: nonce reservations, transaction rows
+    Hot,
+    Standard,
+    /// Aggregations and exports; call sites opt in explicitly
+    Analytics,
+}
+
+impl QueryClass {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QueryClass::Hot => "hot",
+            QueryClass::Standard => "standard",
+            QueryClass::Analytics => "analytics",
+        }
+    }
+}
+
+impl std::fmt::Display for QueryClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryTimeouts {
+    pub hot: Duration,
+    pub standard: Duration,
+    pub analytics: Duration,
+    /// Runs at least this slow are logged
+    pub slow: Duration,
+}
+
+impl Default for QueryTimeouts {
+    fn default() -> Self {
+        Self {
+            hot: Duration::from_secs(2),
+            standard: Duration::from_secs(10),
+            analytics: Duration::from_secs(30),
+            slow: Duration::from_millis(500),
+        }
+    }
+}
+
+impl QueryTimeouts {
+    /// Budget of one run of `class`, connection wait included
+    pub fn budget(&self, class: QueryClass) -> Duration {
+        match class {
+            QueryClass::Hot => self.hot,
+            QueryClass::Standard => self.standard,
+            QueryClass::Analytics => self.analytics,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum QueryError {
+    /// The statement ran past its deadline and was interrupted
+    #[error("database query {query} timed out after {}ms", .timeout.as_millis())]
+    Timeout { query: &'static str, timeout: Duration },
+    /// No pooled connection became free within the budget
+    #[error("no database connection available for {query} after {}ms", .waited.as_millis())]
+    PoolExhausted { query: &'static str, waited: Duration },
+}
+
+/// Longest connection wait seen since the last [`AcquireWait::take`]
+#[derive(Debug, Default)]
+pub struct AcquireWait {
+    max_micros: AtomicU64,
+}
+
+impl AcquireWait {
+    pub fn record(&self, waited: Duration) {
+        self.max_micros.fetch_max(waited.as_micros().min(u64::MAX as u128) as u64, Ordering::Relaxed);
+    }
+
+    /// The longest wait, resetting it for the next sampling period
+    pub fn take(&self) -> Duration {
+        Duration::from_micros(self.max_micros.swap(0, Ordering::Relaxed))
+    }
+}
+
+/// Size, idle connections and longest acquire wait of one pool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolStats {
+    /// `writer` or `reader`
+    pub pool: &'static str,
+    pub size: u32,
+    pub idle: usize,
+    pub max_acquire_wait: Duration,
+}
+
+impl PoolStats {
+    pub fn sample(pool_name: &'static str, pool: &SqlitePool, wait: &AcquireWait) -> Self {
+        Self { pool: pool_name, size: pool.size(), idle: pool.num_idle(), max_acquire_wait: wait.take() }
+    }
+}
+
+/// A pooled connection whose statements are interrupted at the deadline.
+/// Dropped past the deadline, the connection is closed instead of going
+/// back to the pool.
+pub struct GuardedConnection {
+    conn: PoolConnection<Sqlite>,
+    deadline: Instant,
+    state: Arc<AtomicU8>,
+}
+
+impl GuardedConnection {
+    async fn arm(mut conn: PoolConnection<Sqlite>, deadline: Instant) -> Result<Self> {
+        let state = Arc::new(AtomicU8::new(ARMED));
+        let handler_state = state.clone();
+        // replaces the handler a previous run left on this connection
+        conn.lock_handle().await?.set_progress_handler(PROGRESS_OPS, move || {
+            match handler_state.load(Ordering::Relaxed) {
+                ARMED => Instant::now() < deadline,
+                DISARMED => true,
+                _ => false,
+            }
+        });
+        Ok(Self { conn, deadline, state })
+    }
+}
+
+impl Deref for GuardedConnection {
+    type Target = SqliteConnection;
+
+    fn deref(&self) -> &SqliteConnection {
+        &self.conn
+    }
+}
+
+impl DerefMut for GuardedConnection {
+    fn deref_mut(&mut self) -> &mut SqliteConnection {
+        &mut self.conn
+    }
+}
+
+impl Drop for GuardedConnection {
+    fn drop(&mut self) {
+        if Instant::now() >= self.deadline {
+            // a statement may still be running on the worker; stop it and
+            // keep the connection out of the pool
+            self.state.store(CANCELLED, Ordering::Relaxed);
+            self.conn.close_on_drop();
+        } else {
+            self.state.store(DISARMED, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Runs `query` on a connection from `pool` within the budget of `class`.
+///
+/// `query` gets the connection by value; a transaction is begun on it with
+/// `conn.begin()`. Timeouts come back as a [`QueryError`] inside the
+/// `anyhow::Error`.
+pub async fn run<T, F, Fut>(
+    pool: &SqlitePool,
+    wait: &AcquireWait,
+    timeouts: &QueryTimeouts,
+    metrics: Option<&WalletMetrics>,
+    name: &'static str,
+    class: QueryClass,
+    query: F,
+) -> Result<T>
+where
+    F: FnOnce(GuardedConnection) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let budget = timeouts.budget(class);
+    let started = Instant::now();
+    let deadline = started + budget;
+
+    let conn = match tokio::time::timeout_at(deadline.into(), pool.acquire()).await {
+        Ok(Ok(conn)) => conn,
+        Ok(Err(sqlx::Error::PoolTimedOut)) | Err(_) => {
+            let waited = started.elapsed();
+            wait.record(waited);
+            if let Some(metrics) = metrics {
+                metrics.record_database_timeout(name, "acquire");
+            }
+            warn!(query = name, class = %class, waited_ms = waited.as_millis() as u64, "database pool exhausted");
+            return Err(QueryError::PoolExhausted { query: name, waited }.into());
+        }
+        Ok(Err(e)) => return Err(e.into()),
+    };
+    wait.record(started.elapsed());
+
+    let conn = GuardedConnection::arm(conn, deadline).await?;
+    let outcome = tokio::time::timeout_at(deadline.into(), query(conn)).await;
+    let elapsed = started.elapsed();
+    if let Some(metrics) = metrics {
+        metrics.record_database_operation(name, elapsed.as_secs_f64());
+    }
+
+    let result = outcome.unwrap_or_else(|elapsed| Err(elapsed.into()));
+    let timed_out = match &result {
+        // storage code often flattens driver errors into messages, so a
+        // failure past the deadline counts as the interrupt too
+        Err(e) => e.is::<tokio::time::error::Elapsed>() || interrupted(e) || Instant::now() >= deadline,
+        Ok(_) => false,
+    };
+    if timed_out {
+        if let Some(metrics) = metrics {
+            metrics.record_database_timeout(name, "statement");
+        }
+        warn!(query = name, class = %class, timeout_ms = budget.as_millis() as u64, "database query timed out");
+        return Err(QueryError::Timeout { query: name, timeout: budget }.into());
+    }
+    if elapsed >= timeouts.slow {
+        warn!(query = name, class = %class, elapsed_ms = elapsed.as_millis() as u64, "slow database query");
+    }
+    result
+}
+
+fn interrupted(e: &anyhow::Error) -> bool {
+    e.chain().any(|cause| match cause.downcast_ref::<sqlx::Error>() {
+        Some(sqlx::Error::Database(db)) => db.code().as_deref() == Some(SQLITE_INTERRUPT),
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    const RUNAWAY: &str = "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT count(*) FROM n";
+
+    async fn file_pool(dir: &tempfile::TempDir, max_connections: u32) -> SqlitePool {
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("guard.db").display());
+        SqlitePoolOptions::new().max_connections(max_connections).connect(&url).await.unwrap()
+    }
+
+    fn short_timeouts() -> QueryTimeouts {
+        QueryTimeouts { hot: Duration::from_millis(200), ..Default::default() }
+    }
+
+    async fn select_one(pool: &SqlitePool, wait: &AcquireWait, metrics: Option<&WalletMetrics>) -> Result<i64> {
+        run(pool, wait, &short_timeouts(), metrics, "test.select_one", QueryClass::Standard, |mut conn| async move {
+            Ok(sqlx::query_scalar::<_, i64>("SELECT 1").fetch_one(&mut *conn).await?)
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_runaway_query_is_interrupted_without_poisoning_the_pool() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = file_pool(&dir, 1).await;
+        let wait = AcquireWait::default();
+        let metrics = WalletMetrics::new().unwrap();
+
+        let started = Instant::now();
+        let err = run(
+            &pool,
+            &wait,
+            &short_timeouts(),
+            Some(&metrics),
+            "test.runaway",
+            QueryClass::Hot,
+            |mut conn| async move { Ok(sqlx::query_scalar::<_, i64>(RUNAWAY).fetch_one(&mut *conn).await?) },
+        )
+        .await
+        .unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(2), "took {:?}", started.elapsed());
+        assert_eq!(
+            err.downcast_ref::<QueryError>(),
+            Some(&QueryError::Timeout { query: "test.runaway", timeout: Duration::from_millis(200) })
+        );
+        assert_eq!(metrics.database_query_timeouts.with_label_values(&["test.runaway", "statement"]).get(), 1);
+
+        // the single connection was closed and replaced, not left busy
+        assert_eq!(select_one(&pool, &wait, None).await.unwrap(), 1);
+        assert_eq!(sqlx::query_scalar::<_, i64>("SELECT 2").fetch_one(&pool).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_durations_are_labeled_by_query_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = file_pool(&dir, 2).await;
+        let metrics = WalletMetrics::new().unwrap();
+
+        select_one(&pool, &AcquireWait::default(), Some(&metrics)).await.unwrap();
+        select_one(&pool, &AcquireWait::default(), Some(&metrics)).await.unwrap();
+
+        assert_eq!(metrics.database_operations.with_label_values(&["test.select_one"]).get_sample_count(), 2);
+        let text = metrics.export_metrics().unwrap();
+        assert!(text.contains("database_operations_seconds_count{query=\"test.select_one\"} 2"), "{}", text);
+
+        // storage call sites report under their own names
+        let metrics = std::sync::Arc::new(metrics);
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("storage.db").display());
+        let storage = crate::storage::WalletStorage::new_with_url(&url).await.unwrap().with_metrics(metrics.clone());
+        let since = chrono::Utc::now() - chrono::Duration::days(1);
+        storage.fee_analytics("eth", since, crate::storage::FeeGrouping::Day).await.unwrap();
+        assert_eq!(metrics.database_operations.with_label_values(&["fee_history.summarize"]).get_sample_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_wait_reflects_contention() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = file_pool(&dir, 1).await;
+        let wait = AcquireWait::default();
+        let metrics = WalletMetrics::new().unwrap();
+
+        select_one(&pool, &wait, None).await.unwrap();
+        let idle = PoolStats::sample("writer", &pool, &wait);
+        assert!(idle.max_acquire_wait < Duration::from_millis(100), "{:?}", idle);
+
+        let held = pool.acquire().await.unwrap();
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            drop(held);
+        });
+        select_one(&pool, &wait, None).await.unwrap();
+        release.await.unwrap();
+
+        let stats = PoolStats::sample("writer", &pool, &wait);
+        assert!(stats.max_acquire_wait >= Duration::from_millis(100), "{:?}", stats);
+        assert_eq!(stats.size, 1);
+        metrics.set_database_pool_stats(stats.pool, stats.size, stats.idle, stats.max_acquire_wait.as_secs_f64());
+        assert!(metrics.database_pool_acquire_wait.with_label_values(&["writer"]).get() >= 0.1);
+        // the next period starts from zero
+        assert_eq!(wait.take(), Duration::ZERO);
+
+        // the budget runs out while the only connection is held
+        let _held = pool.acquire().await.unwrap();
+        let err = run(&pool, &wait, &short_timeouts(), None, "test.starved", QueryClass::Hot, |mut conn| async move {
+            Ok(sqlx::query_scalar::<_, i64>("SELECT 1").fetch_one(&mut *conn).await?)
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<QueryError>(),
+            Some(QueryError::PoolExhausted { query: "test.starved", .. })
+        ));
+        assert_eq!(crate::core::error_class::classify(&err), crate::core::error_class::ErrorClass::PoolExhausted);
+    }
+}
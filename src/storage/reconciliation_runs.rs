@@ -0,0 +1,426 @@
+//! On-chain reconciliation runs and the discrepancies they found.
+//!
+//! A run is requested for a time range and a set of networks and waits as
+//! `queued` until the reconciliation job claims it; claiming is a
+//! compare-and-set on the status, so two instances never run the same
+//! request. When the run completes its findings are written one row each
+//! under `(run_id, position)`, in the order they were found, together with
+//! the totals on the run row. Reports of any size are read back a page at a
+//! time by position.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+/// Waiting for the reconciliation job
+pub const RUN_QUEUED: &str = "queued";
+pub const RUN_RUNNING: &str = "running";
+pub const RUN_COMPLETED: &str = "completed";
+/// Stopped by an error or shutdown; nothing was stored for it
+pub const RUN_FAILED: &str = "failed";
+
+/// What a stored record and the chain disagree on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscrepancyKind {
+    /// The receipt's outcome (or block) differs from the recorded one
+    StatusMismatch,
+    /// A transaction of one of our addresses has no record
+    MissingRecord,
+    /// A record claims a mined transaction the chain has no receipt for
+    OrphanRecord,
+    /// The recorded fee differs from what the sender paid
+    FeeMismatch,
+    /// The record does not name the wallet's address on the side the chain does
+    UnknownDirection,
+}
+
+impl DiscrepancyKind {
+    pub const ALL: [DiscrepancyKind; 5] = [
+        DiscrepancyKind::StatusMismatch,
+        DiscrepancyKind::MissingRecord,
+        DiscrepancyKind::OrphanRecord,
+        DiscrepancyKind::FeeMismatch,
+        DiscrepancyKind::UnknownDirection,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiscrepancyKind::StatusMismatch => "status_mismatch",
+            DiscrepancyKind::MissingRecord => "missing_record",
+            DiscrepancyKind::OrphanRecord => "orphan_record",
+            DiscrepancyKind::FeeMismatch => "fee_mismatch",
+            DiscrepancyKind::UnknownDirection => "unknown_direction",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == s)
+    }
+}
+
+/// One finding of a run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Discrepancy {
+    pub kind: DiscrepancyKind,
+    pub network: String,
+    pub wallet_id: String,
+    pub tx_hash: String,
+    /// The `transactions` row; `None` for a missing record
+    pub transaction_id: Option<String>,
+    /// Our value of what differs (status, fee in ether, block, address)
+    pub recorded: Option<String>,
+    /// The chain's value of the same
+    pub onchain: Option<String>,
+    pub detail: String,
+    /// Apply mode corrects findings like this one; the others are report-only
+    pub correctable: bool,
+    /// Corrected by the run that found it
+    pub applied: bool,
+}
+
+/// A stored finding and its place in the report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StoredDiscrepancy {
+    pub position: i64,
+    #[serde(flatten)]
+    pub discrepancy: Discrepancy,
+}
+
+/// Counters of a run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconciliationTotals {
+    /// Stored transactions compared with their receipts
+    pub checked: u64,
+    /// On-chain transactions of our addresses looked up in storage
+    pub scanned: u64,
+    /// Pending records without a receipt yet; not a discrepancy
+    pub unmined: u64,
+    pub discrepancies: u64,
+    /// Discrepancies corrected in apply mode
+    pub applied: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReconciliationRun {
+    pub id: String,
+    /// Unix seconds; the range is `range_from..range_to`
+    pub range_from: i64,
+    pub range_to: i64,
+    pub networks: Vec<String>,
+    /// Correct what can be corrected, not just report
+    pub apply: bool,
+    /// [`RUN_QUEUED`], [`RUN_RUNNING`], [`RUN_COMPLETED`] or [`RUN_FAILED`]
+    pub status: String,
+    pub requested_by: String,
+    #[serde(flatten)]
+    pub totals: ReconciliationTotals,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub started_at: Option<i64>,
+    pub completed_at: Option<i64>,
+}
+
+/// A run to queue
+#[derive(Debug, Clone, Copy)]
+pub struct NewReconciliationRun<'a> {
+    pub range_from: i64,
+    pub range_to: i64,
+    pub networks: &'a [String],
+    pub apply: bool,
+    pub requested_by: &'a str,
+}
+
+#[derive(FromRow)]
+struct RunRow {
+    id: String,
+    range_from: i64,
+    range_to: i64,
+    networks: String,
+    apply: bool,
+    status: String,
+    requested_by: String,
+    checked: i64,
+    scanned: i64,
+    unmined: i64,
+    discrepancies: i64,
+    applied: i64,
+    error: Option<String>,
+    created_at: i64,
+    started_at: Option<i64>,
+    completed_at: Option<i64>,
+}
+
+impl RunRow {
+    fn into_run(self) -> Result<ReconciliationRun> {
+        let networks = serde_json::from_str(&self.networks)
+            .map_err(|_| anyhow::anyhow!("Corrupt networks on reconciliation run {}", self.id))?;
+        Ok(ReconciliationRun {
+            range_from: self.range_from,
+            range_to: self.range_to,
+            networks,
+            apply: self.apply,
+            status: self.status,
+            requested_by: self.requested_by,
+            totals: ReconciliationTotals {
+                checked: self.checked as u64,
+                scanned: self.scanned as u64,
+                unmined: self.unmined as u64,
+                discrepancies: self.discrepancies as u64,
+                applied: self.applied as u64,
+            },
+            error: self.error,
+            created_at: self.created_at,
+            started_at: self.started_at,
+            completed_at: self.completed_at,
+            id: self.id,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct DiscrepancyRow {
+    position: i64,
+    kind: String,
+    network: String,
+    wallet_id: String,
+    tx_hash: String,
+    transaction_id: Option<String>,
+    recorded: Option<String>,
+    onchain: Option<String>,
+    detail: String,
+    correctable: bool,
+    applied: bool,
+}
+
+impl DiscrepancyRow {
+    fn into_stored(self) -> Result<StoredDiscrepancy> {
+        let kind = DiscrepancyKind::parse(&self.kind)
+            .ok_or_else(|| anyhow::anyhow!("Unknown discrepancy kind {:?}", self.kind))?;
+        Ok(StoredDiscrepancy {
+            position: self.position,
+            discrepancy: Discrepancy {
+                kind,
+                network: self.network,
+                wallet_id: self.wallet_id,
+                tx_hash: self.tx_hash,
+                transaction_id: self.transaction_id,
+                recorded: self.recorded,
+                onchain: self.onchain,
+                detail: self.detail,
+                correctable: self.correctable,
+                applied: self.applied,
+            },
+        })
+    }
+}
+
+const RUN_COLUMNS: &str = "id, range_from, range_to, networks, apply, status, requested_by, checked, scanned, \
+     unmined, discrepancies, applied, error, created_at, started_at, completed_at";
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS reconciliation_runs (
+            id TEXT PRIMARY KEY,
+            range_from INTEGER NOT NULL,
+            range_to INTEGER NOT NULL,
+            networks TEXT NOT NULL,
+            apply INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            requested_by TEXT NOT NULL,
+            checked INTEGER NOT NULL DEFAULT 0,
+            scanned INTEGER NOT NULL DEFAULT 0,
+            unmined INTEGER NOT NULL DEFAULT 0,
+            discrepancies INTEGER NOT NULL DEFAULT 0,
+            applied INTEGER NOT NULL DEFAULT 0,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            started_at INTEGER,
+            completed_at INTEGER
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_reconciliation_runs_status ON reconciliation_runs (status, created_at)",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS reconciliation_discrepancies (
+            run_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            network TEXT NOT NULL,
+            wallet_id TEXT NOT NULL,
+            tx_hash TEXT NOT NULL,
+            transaction_id TEXT,
+            recorded TEXT,
+            onchain TEXT,
+            detail TEXT NOT NULL,
+            correctable INTEGER NOT NULL,
+            applied INTEGER NOT NULL,
+            PRIMARY KEY (run_id, position)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn insert(pool: &SqlitePool, id: &str, run: &NewReconciliationRun<'_>, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO reconciliation_runs (id, range_from, range_to, networks, apply, status, requested_by, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        "#,
+    )
+    .bind(id)
+    .bind(run.range_from)
+    .bind(run.range_to)
+    .bind(serde_json::to_string(run.networks)?)
+    .bind(run.apply)
+    .bind(RUN_QUEUED)
+    .bind(run.requested_by)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to queue reconciliation run: {}", e))?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, id: &str) -> Result<Option<ReconciliationRun>> {
+    sqlx::query_as::<_, RunRow>(&format!("SELECT {} FROM reconciliation_runs WHERE id = ?1", RUN_COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .map(RunRow::into_run)
+        .transpose()
+}
+
+/// Moves the oldest queued run to `running`; `None` when nothing is queued.
+pub async fn claim_next(pool: &SqlitePool, now: i64) -> Result<Option<ReconciliationRun>> {
+    loop {
+        let Some(id) = sqlx::query_scalar::<_, String>(
+            "SELECT id FROM reconciliation_runs WHERE status = ?1 ORDER BY created_at, id LIMIT 1",
+        )
+        .bind(RUN_QUEUED)
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let claimed =
+            sqlx::query("UPDATE reconciliation_runs SET status = ?1, started_at = ?2 WHERE id = ?3 AND status = ?4")
+                .bind(RUN_RUNNING)
+                .bind(now)
+                .bind(&id)
+                .bind(RUN_QUEUED)
+                .execute(pool)
+                .await?;
+        // lost the race to another instance; try the next one
+        if claimed.rows_affected() == 1 {
+            return get(pool, &id).await;
+        }
+    }
+}
+
+/// Stores the findings of a running run and completes it.
+pub async fn complete(
+    conn: &mut SqliteConnection,
+    id: &str,
+    totals: &ReconciliationTotals,
+    discrepancies: &[Discrepancy],
+    now: i64,
+) -> Result<bool> {
+    let updated = sqlx::query(
+        r#"
+        UPDATE reconciliation_runs
+        SET status = ?1, checked = ?2, scanned = ?3, unmined = ?4, discrepancies = ?5, applied = ?6,
+            completed_at = ?7
+        WHERE id = ?8 AND status = ?9
+        "#,
+    )
+    .bind(RUN_COMPLETED)
+    .bind(totals.checked as i64)
+    .bind(totals.scanned as i64)
+    .bind(totals.unmined as i64)
+    .bind(totals.discrepancies as i64)
+    .bind(totals.applied as i64)
+    .bind(now)
+    .bind(id)
+    .bind(RUN_RUNNING)
+    .execute(&mut *conn)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Ok(false);
+    }
+    for (position, d) in discrepancies.iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO reconciliation_discrepancies (run_id, position, kind, network, wallet_id, tx_hash,
+                                                      transaction_id, recorded, onchain, detail, correctable, applied)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            "#,
+        )
+        .bind(id)
+        .bind(position as i64)
+        .bind(d.kind.as_str())
+        .bind(&d.network)
+        .bind(&d.wallet_id)
+        .bind(&d.tx_hash)
+        .bind(&d.transaction_id)
+        .bind(&d.recorded)
+        .bind(&d.onchain)
+        .bind(&d.detail)
+        .bind(d.correctable)
+        .bind(d.applied)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to store reconciliation finding: {}", e))?;
+    }
+    Ok(true)
+}
+
+pub async fn fail(pool: &SqlitePool, id: &str, error: &str, now: i64) -> Result<()> {
+    sqlx::query("UPDATE reconciliation_runs SET status = ?1, error = ?2, completed_at = ?3 WHERE id = ?4")
+        .bind(RUN_FAILED)
+        .bind(error)
+        .bind(now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Findings of `run_id` after position `after` (from the start when `None`),
+/// in report order.
+pub async fn discrepancies(
+    pool: &SqlitePool,
+    run_id: &str,
+    after: Option<i64>,
+    limit: usize,
+) -> Result<Vec<StoredDiscrepancy>> {
+    sqlx::query_as::<_, DiscrepancyRow>(
+        r#"
+        SELECT position, kind, network, wallet_id, tx_hash, transaction_id, recorded, onchain, detail, correctable,
+               applied
+        FROM reconciliation_discrepancies
+        WHERE run_id = ?1 AND position > ?2
+        ORDER BY position
+        LIMIT ?3
+        "#,
+    )
+    .bind(run_id)
+    .bind(after.unwrap_or(-1))
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(DiscrepancyRow::into_stored)
+    .collect()
+}
@@ -0,0 +1,56 @@
+//! Nonces of signed API requests, shared by every instance on the database.
+//!
+//! A nonce is claimed once per signing key and held until `expires_at`, which
+//! callers set past the end of the timestamp window the signature was accepted
+//! in; after that the timestamp check alone rejects a replay and the row can go.
+
+use anyhow::Result;
+use sqlx::sqlite::SqlitePool;
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS request_nonces (
+            key_id TEXT NOT NULL,
+            nonce TEXT NOT NULL,
+            expires_at INTEGER NOT NULL,
+            PRIMARY KEY (key_id, nonce)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_request_nonces_expiry ON request_nonces (expires_at)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// `false` when the nonce is already held for this key. A row left behind by
+/// an expired claim is taken over.
+pub async fn claim(pool: &SqlitePool, key_id: &str, nonce: &str, expires_at: i64, now: i64) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO request_nonces (key_id, nonce, expires_at) VALUES (?1, ?2, ?3)
+        ON CONFLICT (key_id, nonce) DO UPDATE SET expires_at = excluded.expires_at
+        WHERE request_nonces.expires_at <= ?4
+        "#,
+    )
+    .bind(key_id)
+    .bind(nonce)
+    .bind(expires_at)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record request nonce: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn purge_expired(pool: &SqlitePool, now: i64) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM request_nonces WHERE expires_at <= ?1")
+        .bind(now)
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to purge request nonces: {}", e))?;
+    Ok(result.rows_affected())
+}
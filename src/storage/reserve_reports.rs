@@ -0,0 +1,118 @@
+//! Signed proof-of-reserves reports.
+//!
+//! The report is stored as the exact canonical JSON that was signed, so a
+//! retrieved document verifies byte for byte. Row counts are denormalized for
+//! the list view, which never loads documents.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow};
+
+/// List-view fields of a stored report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct ReserveReportSummary {
+    pub id: String,
+    pub group_name: Option<String>,
+    pub signer: String,
+    pub rows: i64,
+    pub unverified_rows: i64,
+    /// Unix seconds
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct ReserveReportRecord {
+    pub id: String,
+    pub group_name: Option<String>,
+    pub signer: String,
+    pub signature: String,
+    /// Canonical JSON of the signed report
+    pub document: String,
+    pub rows: i64,
+    pub unverified_rows: i64,
+    pub created_at: i64,
+}
+
+/// Fields of a new `reserve_reports` row
+#[derive(Debug, Clone)]
+pub struct NewReserveReport<'a> {
+    pub group_name: Option<&'a str>,
+    pub signer: &'a str,
+    pub signature: &'a str,
+    pub document: &'a str,
+    pub rows: i64,
+    pub unverified_rows: i64,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS reserve_reports (
+            id TEXT PRIMARY KEY,
+            group_name TEXT,
+            signer TEXT NOT NULL,
+            signature TEXT NOT NULL,
+            document TEXT NOT NULL,
+            rows INTEGER NOT NULL,
+            unverified_rows INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_reserve_reports_created ON reserve_reports(created_at)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn insert(pool: &SqlitePool, id: &str, report: &NewReserveReport<'_>, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO reserve_reports (id, group_name, signer, signature, document, rows, unverified_rows, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        "#,
+    )
+    .bind(id)
+    .bind(report.group_name)
+    .bind(report.signer)
+    .bind(report.signature)
+    .bind(report.document)
+    .bind(report.rows)
+    .bind(report.unverified_rows)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store reserve report: {}", e))?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, id: &str) -> Result<Option<ReserveReportRecord>> {
+    sqlx::query_as::<_, ReserveReportRecord>(
+        "SELECT id, group_name, signer, signature, document, rows, unverified_rows, created_at \
+         FROM reserve_reports WHERE id = ?1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load reserve report: {}", e))
+}
+
+/// Newest first, with the total count
+pub async fn list(pool: &SqlitePool, offset: usize, limit: usize) -> Result<(Vec<ReserveReportSummary>, usize)> {
+    let rows = sqlx::query_as::<_, ReserveReportSummary>(
+        "SELECT id, group_name, signer, rows, unverified_rows, created_at FROM reserve_reports \
+         ORDER BY created_at DESC, id DESC LIMIT ?1 OFFSET ?2",
+    )
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to list reserve reports: {}", e))?;
+    let (total,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM reserve_reports")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to count reserve reports: {}", e))?;
+    Ok((rows, total as usize))
+}
@@ -0,0 +1,508 @@
+//! Sandbox databases and the source side of `sandbox-clone`.
+//!
+//! A sandbox is a staging database whose wallets and records were copied
+//! from another database while every key was generated fresh. The
+//! `system_settings` row `sandbox = true` marks it; a server started on a
+//! marked database drops the mainnet networks from its configuration.
+//!
+//! The copy itself runs on one write connection with the source attached as
+//! `sandbox_source`, so most tables are copied with `INSERT ... SELECT`. The
+//! old → new address pairs sit in a temp table the statements join against;
+//! text columns that may mention an address (audit details, multisig signer
+//! lists) are rewritten in Rust.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePool, SqlitePoolOptions};
+
+pub const SANDBOX_SETTING: &str = "sandbox";
+
+/// Schema name the source database is attached under while rows are copied
+pub(super) const SOURCE_SCHEMA: &str = "sandbox_source";
+
+/// Tables holding key material or secrets derived from the source keys;
+/// they are never copied.
+pub const SKIPPED_TABLES: &[&str] = &[
+    "api_keys",
+    "approval_keys",
+    "deadman_policies",
+    "delegations",
+    "key_versions",
+    "signing_intents",
+    "timelocks",
+    "wallet_tokens",
+];
+
+/// Reason recorded on attestations copied into a sandbox; their signatures
+/// were made with the source keys.
+pub const CLONED_ATTESTATION_REASON: &str = "sandbox clone";
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS system_settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn is_sandbox(pool: &SqlitePool) -> Result<bool> {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM system_settings WHERE key = ?1")
+        .bind(SANDBOX_SETTING)
+        .fetch_optional(pool)
+        .await?;
+    Ok(value.as_deref() == Some("true"))
+}
+
+pub async fn mark(conn: &mut SqliteConnection, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO system_settings (key, value, updated_at) VALUES (?1, 'true', ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(SANDBOX_SETTING)
+    .bind(now)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Whether the database at `url` is marked as a sandbox, read without
+/// creating or migrating it. A database that does not exist yet, or predates
+/// `system_settings`, is not a sandbox. Only SQLite databases are cloned into sandboxes.
+pub async fn probe(url: &str) -> Result<bool> {
+    if !url.starts_with("sqlite:") {
+        return Ok(false);
+    }
+    let Some(path) = database_path(url)? else {
+        return Ok(false);
+    };
+    if !path.exists() {
+        return Ok(false);
+    }
+    let pool = open_read_only(&path).await?;
+    let has_settings: bool =
+        sqlx::query_scalar("SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'system_settings'")
+            .fetch_one(&pool)
+            .await?;
+    let sandbox = has_settings && is_sandbox(&pool).await?;
+    pool.close().await;
+    Ok(sandbox)
+}
+
+/// File behind a `sqlite:` URL; `None` for in-memory databases.
+pub fn database_path(url: &str) -> Result<Option<PathBuf>> {
+    if url.contains(":memory:") || url.contains("mode=memory") {
+        return Ok(None);
+    }
+    let options = SqliteConnectOptions::from_str(url).map_err(|e| anyhow::anyhow!("Invalid database URL: {}", e))?;
+    Ok(Some(options.get_filename().to_path_buf()))
+}
+
+async fn open_read_only(path: &Path) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::new().filename(path).read_only(true).create_if_missing(false);
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))
+}
+
+/// A wallet in the source database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceWallet {
+    pub id: String,
+    pub name: String,
+    pub quantum_safe: bool,
+}
+
+/// Read-only handle on the database a sandbox is cloned from
+pub struct CloneSource {
+    path: PathBuf,
+    pool: SqlitePool,
+}
+
+impl CloneSource {
+    pub async fn open(url: &str) -> Result<Self> {
+        let path = database_path(url)?.ok_or_else(|| anyhow::anyhow!("cannot clone from an in-memory database"))?;
+        if !path.exists() {
+            anyhow::bail!("source database {} does not exist", path.display());
+        }
+        let pool = open_read_only(&path).await?;
+        Ok(Self { path, pool })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Completely created wallets, by name
+    pub async fn wallets(&self) -> Result<Vec<SourceWallet>> {
+        let rows: Vec<(String, String, bool)> = sqlx::query_as(
+            "SELECT id, name, quantum_safe FROM wallets WHERE creation_state = 'complete' ORDER BY name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|(id, name, quantum_safe)| SourceWallet { id, name, quantum_safe }).collect())
+    }
+
+    /// `(network, address)` pairs the wallet uses, sorted. Registered
+    /// networks come from `wallet_networks`; wallets created before it
+    /// existed fall back to the sender addresses of their transactions.
+    pub async fn addresses(&self, wallet_name: &str) -> Result<Vec<(String, String)>> {
+        let tables = self.tables().await?;
+        let mut pairs: Vec<(String, String)> = Vec::new();
+        if tables.contains("wallet_networks") {
+            pairs = sqlx::query_as("SELECT network, address FROM wallet_networks WHERE wallet_name = ?1")
+                .bind(wallet_name)
+                .fetch_all(&self.pool)
+                .await?;
+        }
+        // Sources from before schema version 18 may key transactions by wallet name
+        let sent: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT network, from_address FROM transactions \
+             WHERE wallet_id IN (?1, (SELECT id FROM wallets WHERE name = ?1))",
+        )
+        .bind(wallet_name)
+        .fetch_all(&self.pool)
+        .await?;
+        for (network, address) in sent {
+            if !pairs.iter().any(|(n, _)| *n == network) {
+                pairs.push((network, address));
+            }
+        }
+        pairs.sort();
+        Ok(pairs)
+    }
+
+    async fn tables(&self) -> Result<HashSet<String>> {
+        let names: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table'").fetch_all(&self.pool).await?;
+        Ok(names.into_iter().collect())
+    }
+
+    pub async fn close(self) {
+        self.pool.close().await;
+    }
+}
+
+/// Source → sandbox address pairs, keyed by network and lowercased source
+/// address. Iteration order is fixed, so a run rewrites deterministically.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddressRewrites {
+    pairs: BTreeMap<(String, String), String>,
+}
+
+/// One entry of [`AddressRewrites`], as reported
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddressRewrite {
+    pub network: String,
+    pub source: String,
+    pub sandbox: String,
+}
+
+impl AddressRewrites {
+    pub fn insert(&mut self, network: &str, source: &str, sandbox: &str) {
+        self.pairs.insert((network.to_string(), source.to_lowercase()), sandbox.to_string());
+    }
+
+    pub fn get(&self, network: &str, source: &str) -> Option<&str> {
+        self.pairs.get(&(network.to_string(), source.to_lowercase())).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    pub fn entries(&self) -> Vec<AddressRewrite> {
+        self.pairs
+            .iter()
+            .map(|((network, source), sandbox)| AddressRewrite {
+                network: network.clone(),
+                source: source.clone(),
+                sandbox: sandbox.clone(),
+            })
+            .collect()
+    }
+
+    /// `address` rewritten on `network`, unchanged when it is not a source address
+    pub fn address(&self, network: &str, address: &str) -> String {
+        self.get(network, address).map(str::to_string).unwrap_or_else(|| address.to_string())
+    }
+
+    /// Every source address in `text` replaced, matched case-insensitively
+    /// and regardless of network. The first network in order wins for an
+    /// address registered on several.
+    pub fn text(&self, text: &str) -> String {
+        let mut seen = HashSet::new();
+        let mut out = text.to_string();
+        for ((_, source), sandbox) in &self.pairs {
+            if seen.insert(source.as_str()) {
+                out = replace_ignore_ascii_case(&out, source, sandbox);
+            }
+        }
+        out
+    }
+}
+
+fn replace_ignore_ascii_case(text: &str, needle: &str, replacement: &str) -> String {
+    let (haystack, pattern) = (text.as_bytes(), needle.as_bytes());
+    if pattern.is_empty() || !needle.is_ascii() {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let (mut start, mut i) = (0, 0);
+    while i + pattern.len() <= haystack.len() {
+        if haystack[i..i + pattern.len()].eq_ignore_ascii_case(pattern) {
+            // `needle` is ASCII, so a match starts and ends on char boundaries
+            out.push_str(&text[start..i]);
+            out.push_str(replacement);
+            i += pattern.len();
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+    out.push_str(&text[start..]);
+    out
+}
+
+/// Loads `rewrites` into the connection's temp table the copy statements join against.
+pub(super) async fn load_rewrites(conn: &mut SqliteConnection, rewrites: &AddressRewrites) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TEMP TABLE IF NOT EXISTS sandbox_addresses (
+            network TEXT NOT NULL,
+            source TEXT NOT NULL,
+            sandbox TEXT NOT NULL,
+            PRIMARY KEY (network, source)
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query("DELETE FROM temp.sandbox_addresses").execute(&mut *conn).await?;
+    for ((network, source), sandbox) in &rewrites.pairs {
+        sqlx::query("INSERT INTO temp.sandbox_addresses (network, source, sandbox) VALUES (?1, ?2, ?3)")
+            .bind(network)
+            .bind(source)
+            .bind(sandbox)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// SQL for `s.<column>` rewritten through `temp.sandbox_addresses` on `s.network`
+fn rewritten(column: &str) -> String {
+    format!(
+        "COALESCE((SELECT m.sandbox FROM temp.sandbox_addresses m WHERE m.network = s.network AND m.source = \
+         lower(s.{column})), s.{column})"
+    )
+}
+
+/// Tables present in the attached source
+pub(super) async fn source_tables(conn: &mut SqliteConnection) -> Result<HashSet<String>> {
+    let names: Vec<String> =
+        sqlx::query_scalar(&format!("SELECT name FROM {}.sqlite_master WHERE type = 'table'", SOURCE_SCHEMA))
+            .fetch_all(conn)
+            .await?;
+    Ok(names.into_iter().collect())
+}
+
+/// Copies the wallet's rows of the tables that need no Rust-side rewriting
+/// from the attached source; returns rows written per table. Rows keep their
+/// ids, so a second run overwrites instead of duplicating.
+pub(super) async fn copy_rows(
+    conn: &mut SqliteConnection,
+    present: &HashSet<String>,
+    wallet_name: &str,
+    now: i64,
+) -> Result<BTreeMap<&'static str, u64>> {
+    let src = SOURCE_SCHEMA;
+    let statements: Vec<(&'static str, String)> = vec![
+        (
+            "bridge_transactions",
+            format!(
+                "INSERT OR REPLACE INTO bridge_transactions (id, from_wallet, from_chain, to_chain, token, amount, \
+                 status, source_tx_hash, destination_tx_hash, created_at, updated_at, fee_amount, \
+                 estimated_completion_time) \
+                 SELECT id, from_wallet, from_chain, to_chain, token, amount, status, source_tx_hash, \
+                 destination_tx_hash, created_at, updated_at, fee_amount, estimated_completion_time \
+                 FROM {src}.bridge_transactions s WHERE s.from_wallet = ?1"
+            ),
+        ),
+        (
+            "address_book",
+            format!(
+                "INSERT OR REPLACE INTO address_book (wallet_name, network, address, label, tags, created_at, \
+                 updated_at) \
+                 SELECT wallet_name, network, {}, label, tags, created_at, updated_at \
+                 FROM {src}.address_book s WHERE s.wallet_name = ?1",
+                rewritten("address")
+            ),
+        ),
+        (
+            "ledger_entries",
+            format!(
+                "INSERT INTO ledger_entries (wallet_id, network, address, tx_hash, log_index, block_number, \
+                 direction, token, counterparty, amount, fee, success, occurred_at, source, created_at) \
+                 SELECT wallet_id, network, {}, tx_hash, log_index, block_number, direction, token, {}, amount, \
+                 fee, success, occurred_at, source, created_at \
+                 FROM {src}.ledger_entries s WHERE s.wallet_id = ?1 \
+                 ON CONFLICT(wallet_id, network, tx_hash, log_index) DO UPDATE SET \
+                 address = excluded.address, counterparty = excluded.counterparty, direction = excluded.direction, \
+                 amount = excluded.amount, fee = excluded.fee, success = excluded.success",
+                rewritten("address"),
+                rewritten("counterparty")
+            ),
+        ),
+        (
+            "balance_subscriptions",
+            format!(
+                "INSERT OR REPLACE INTO balance_subscriptions (id, wallet_id, wallet_name, owner_user_id, network, \
+                 token, min_delta, webhook_target, active, last_notified_balance, pending_since, pending_tx_hash, \
+                 created_by, created_at, updated_at) \
+                 SELECT id, wallet_id, wallet_name, owner_user_id, network, token, min_delta, webhook_target, \
+                 active, last_notified_balance, pending_since, pending_tx_hash, created_by, created_at, updated_at \
+                 FROM {src}.balance_subscriptions s WHERE s.wallet_name = ?1"
+            ),
+        ),
+        (
+            "attestations",
+            format!(
+                "INSERT OR REPLACE INTO attestations (id, wallet_name, network, scheme, address, challenge, \
+                 signature, audience, created_at, expires_at, revoked_at, revoked_reason) \
+                 SELECT id, wallet_name, network, scheme, {}, challenge, signature, audience, created_at, \
+                 expires_at, COALESCE(revoked_at, ?2), COALESCE(revoked_reason, ?3) \
+                 FROM {src}.attestations s WHERE s.wallet_name = ?1",
+                rewritten("address")
+            ),
+        ),
+        (
+            "review_thresholds",
+            format!(
+                "INSERT OR REPLACE INTO review_thresholds (wallet_name, threshold, updated_by, updated_at) \
+                 SELECT wallet_name, threshold, updated_by, updated_at \
+                 FROM {src}.review_thresholds s WHERE s.wallet_name = ?1"
+            ),
+        ),
+        (
+            "spending_limits",
+            format!(
+                "INSERT OR REPLACE INTO spending_limits (wallet_name, network, max_per_tx, max_per_day, updated_by, \
+                 updated_at) \
+                 SELECT wallet_name, network, max_per_tx, max_per_day, updated_by, updated_at \
+                 FROM {src}.spending_limits s WHERE s.wallet_name = ?1"
+            ),
+        ),
+        (
+            "wallet_profiles",
+            format!(
+                "INSERT OR REPLACE INTO wallet_profiles (wallet_id, network, sample_count, mean_amount, m2_amount, \
+                 max_amount, amount_buckets, hour_histogram, distinct_recipients, first_send_at, last_send_at, \
+                 updated_at) \
+                 SELECT wallet_id, network, sample_count, mean_amount, m2_amount, max_amount, amount_buckets, \
+                 hour_histogram, distinct_recipients, first_send_at, last_send_at, updated_at \
+                 FROM {src}.wallet_profiles s WHERE s.wallet_id = ?1"
+            ),
+        ),
+        (
+            "wallet_profile_recipients",
+            format!(
+                "INSERT OR REPLACE INTO wallet_profile_recipients (wallet_id, network, recipient, first_paid_at) \
+                 SELECT wallet_id, network, {}, first_paid_at \
+                 FROM {src}.wallet_profile_recipients s WHERE s.wallet_id = ?1",
+                rewritten("recipient")
+            ),
+        ),
+    ];
+
+    let mut counts = BTreeMap::new();
+    for (table, sql) in statements {
+        if !present.contains(table) {
+            continue;
+        }
+        let mut query = sqlx::query(&sql).bind(wallet_name);
+        if table == "attestations" {
+            query = query.bind(now).bind(CLONED_ATTESTATION_REASON);
+        }
+        let result = query.execute(&mut *conn).await.map_err(|e| anyhow::anyhow!("Failed to copy {}: {}", table, e))?;
+        counts.insert(table, result.rows_affected());
+    }
+
+    if present.contains("wallets") {
+        sqlx::query(&format!(
+            "UPDATE wallets SET description = s.description, metadata = s.metadata \
+             FROM (SELECT description, metadata FROM {src}.wallets WHERE name = ?1) s WHERE wallets.name = ?1"
+        ))
+        .bind(wallet_name)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to copy wallet notes: {}", e))?;
+    }
+    Ok(counts)
+}
+
+/// Multisig policies of the wallet, signer lists rewritten
+pub(super) async fn copy_multisig_policies(
+    conn: &mut SqliteConnection,
+    wallet_name: &str,
+    rewrites: &AddressRewrites,
+) -> Result<u64> {
+    let rows: Vec<(String, String, String, String, i64)> = sqlx::query_as(&format!(
+        "SELECT network, signers, tiers, updated_by, updated_at FROM {}.multisig_policies WHERE wallet_name = ?1",
+        SOURCE_SCHEMA
+    ))
+    .bind(wallet_name)
+    .fetch_all(&mut *conn)
+    .await?;
+    for (network, signers, tiers, updated_by, updated_at) in &rows {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO multisig_policies (wallet_name, network, signers, tiers, updated_by, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(wallet_name)
+        .bind(network)
+        .bind(rewrites.text(signers))
+        .bind(tiers)
+        .bind(updated_by)
+        .bind(updated_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(rows.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_rewrite_ignores_case() {
+        let mut rewrites = AddressRewrites::default();
+        rewrites.insert("eth", "0xAbC0000000000000000000000000000000000001", "0xnew1");
+        rewrites.insert("polygon", "0xabc0000000000000000000000000000000000001", "0xnew2");
+        let text = r#"{"from":"0xABC0000000000000000000000000000000000001","note":"费用"}"#;
+        assert_eq!(rewrites.text(text), r#"{"from":"0xnew1","note":"费用"}"#);
+        assert_eq!(rewrites.address("polygon", "0xABC0000000000000000000000000000000000001"), "0xnew2");
+        assert_eq!(rewrites.address("eth", "0xdead"), "0xdead");
+    }
+}
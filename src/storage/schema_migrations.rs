@@ -0,0 +1,187 @@
+//! Schema version bookkeeping.
+//!
+//! The tables are created idempotently at startup, so the schema has no
+//! migration files to count. Instead [`SCHEMA_VERSION`] names the shape that
+//! `initialize_schema` produces; startup records it in `schema_migrations`,
+//! and the highest recorded version is what the instance reports. An instance
+//! whose own version is lower than the recorded one is running older code
+//! against a database a newer build has already touched.
+
+use anyhow::Result;
+use sqlx::sqlite::SqlitePool;
+
+use super::wallet_tokens::{insert_api_key, AUTH_MODE_BEARER, AUTH_MODE_HMAC};
+use super::{TransactionRecord, WalletStorage};
+use crate::security::signing_secret::SealedSigningSecret;
+
+/// Bump whenever `initialize_schema` adds or changes a table, column or index.
+pub const SCHEMA_VERSION: i64 = 18;
+
+/// First version that keeps token credentials in `api_keys`
+pub const API_KEYS_TABLE: i64 = 17;
+
+/// First version whose `transactions.wallet_id` is always a `wallets.id`
+pub const TRANSACTIONS_KEYED_BY_WALLET_ID: i64 = 18;
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            applied_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Records that this build's schema has been applied; a no-op on restart.
+pub async fn record(pool: &SqlitePool, now: i64) -> Result<()> {
+    sqlx::query("INSERT OR IGNORE INTO schema_migrations (version, applied_at) VALUES (?1, ?2)")
+        .bind(SCHEMA_VERSION)
+        .bind(now)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Highest version any build has recorded; 0 on a database never initialized.
+pub async fn current(pool: &SqlitePool) -> Result<i64> {
+    let version: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM schema_migrations")
+        .fetch_one(pool)
+        .await?;
+    Ok(version.unwrap_or(0))
+}
+
+/// Version 17: gives every wallet token its `api_keys` row.
+///
+/// Tokens from before signing keys existed are bearer keys. Version 16
+/// databases kept `auth_mode` and a plaintext `signing_secret` on
+/// `wallet_tokens`; those secrets are sealed under the KEK on the way over and
+/// both columns are dropped, so no plaintext secret is left in the database
+/// or its backups. Runs after `api_keys` is created.
+pub async fn move_token_credentials(pool: &SqlitePool) -> Result<()> {
+    if current(pool).await? >= API_KEYS_TABLE {
+        return Ok(());
+    }
+    let has_auth_mode: bool =
+        sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_table_info('wallet_tokens') WHERE name = 'auth_mode'")
+            .fetch_one(pool)
+            .await?;
+
+    let mut tx = pool.begin().await?;
+    if has_auth_mode {
+        let signing_keys: Vec<(String, String)> = sqlx::query_as(
+            "SELECT id, signing_secret FROM wallet_tokens WHERE auth_mode = ?1 AND signing_secret IS NOT NULL",
+        )
+        .bind(AUTH_MODE_HMAC)
+        .fetch_all(&mut *tx)
+        .await?;
+        for (id, secret) in signing_keys {
+            let sealed = SealedSigningSecret::seal(&id, &secret)
+                .map_err(|e| anyhow::anyhow!("Failed to seal the signing secret of {}: {}", id, e))?;
+            insert_api_key(&mut tx, &id, AUTH_MODE_HMAC, Some(&sealed)).await?;
+        }
+        sqlx::query("ALTER TABLE wallet_tokens DROP COLUMN signing_secret").execute(&mut *tx).await?;
+        sqlx::query("ALTER TABLE wallet_tokens DROP COLUMN auth_mode").execute(&mut *tx).await?;
+    }
+    sqlx::query(
+        "INSERT INTO api_keys (key_id, auth_mode) \
+         SELECT id, ?1 FROM wallet_tokens WHERE id NOT IN (SELECT key_id FROM api_keys)",
+    )
+    .bind(AUTH_MODE_BEARER)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to move wallet token credentials: {}", e))?;
+    Ok(())
+}
+
+/// Version 18: `transactions.wallet_id` holds `wallets.id` under
+/// `FOREIGN KEY (wallet_id) REFERENCES wallets (id)`.
+///
+/// Versions 16 and 17 dropped the constraint and let the signing intent log,
+/// history backfill and user operations write the wallet name instead. Those
+/// rows are rewritten to the wallet's id and re-hashed; rows naming a wallet
+/// that no longer exists cannot satisfy the constraint and are moved to
+/// `orphaned_transactions` rather than lost. The table is then rebuilt with the
+/// constraint if it lacks it, copying rows column by column; the indexes go
+/// with the old table, so this must run before they are created.
+pub async fn key_transactions_by_wallet_id(pool: &SqlitePool) -> Result<()> {
+    if current(pool).await? >= TRANSACTIONS_KEYED_BY_WALLET_ID {
+        return Ok(());
+    }
+    const COLUMNS: &str = "id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, \
+                           created_at, confirmed_at, integrity_hash, fee_actual, block_number";
+    const COLUMN_DEFS: &str = "id TEXT PRIMARY KEY, wallet_id TEXT NOT NULL, tx_hash TEXT NOT NULL, \
+                               network TEXT NOT NULL, from_address TEXT NOT NULL, to_address TEXT NOT NULL, \
+                               amount TEXT NOT NULL, fee TEXT NOT NULL, status TEXT NOT NULL, \
+                               created_at DATETIME NOT NULL, confirmed_at DATETIME, integrity_hash TEXT NOT NULL, \
+                               fee_actual TEXT, block_number INTEGER";
+    let mut tx = pool.begin().await?;
+    let named: Vec<TransactionRecord> = sqlx::query_as(&format!(
+        "SELECT {} FROM transactions WHERE wallet_id NOT IN (SELECT id FROM wallets) \
+         AND wallet_id IN (SELECT name FROM wallets)",
+        COLUMNS
+    ))
+    .fetch_all(&mut *tx)
+    .await?;
+    for mut record in named {
+        // the integrity hash covers wallet_id; one that did not verify before is left failing
+        let intact = WalletStorage::calculate_transaction_integrity_hash(&record) == record.integrity_hash;
+        record.wallet_id = sqlx::query_scalar("SELECT id FROM wallets WHERE name = ?1")
+            .bind(&record.wallet_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if intact {
+            record.integrity_hash = WalletStorage::calculate_transaction_integrity_hash(&record);
+        }
+        sqlx::query("UPDATE transactions SET wallet_id = ?1, integrity_hash = ?2 WHERE id = ?3")
+            .bind(&record.wallet_id)
+            .bind(&record.integrity_hash)
+            .bind(&record.id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let orphans: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM transactions WHERE wallet_id NOT IN (SELECT id FROM wallets)")
+            .fetch_one(&mut *tx)
+            .await?;
+    if orphans > 0 {
+        sqlx::query(&format!("CREATE TABLE IF NOT EXISTS orphaned_transactions ({})", COLUMN_DEFS))
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!(
+            "INSERT OR IGNORE INTO orphaned_transactions ({0}) SELECT {0} FROM transactions \
+             WHERE wallet_id NOT IN (SELECT id FROM wallets)",
+            COLUMNS
+        ))
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM transactions WHERE wallet_id NOT IN (SELECT id FROM wallets)")
+            .execute(&mut *tx)
+            .await?;
+        tracing::warn!("moved {} transactions of deleted wallets to orphaned_transactions", orphans);
+    }
+
+    let has_fk: bool =
+        sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_foreign_key_list('transactions') WHERE \"table\" = 'wallets'")
+            .fetch_one(&mut *tx)
+            .await?;
+    if !has_fk {
+        sqlx::query(&format!(
+            "CREATE TABLE transactions_v18 ({}, FOREIGN KEY (wallet_id) REFERENCES wallets (id))",
+            COLUMN_DEFS
+        ))
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(&format!("INSERT INTO transactions_v18 ({0}) SELECT {0} FROM transactions", COLUMNS))
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DROP TABLE transactions").execute(&mut *tx).await?;
+        sqlx::query("ALTER TABLE transactions_v18 RENAME TO transactions").execute(&mut *tx).await?;
+    }
+    tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to key transactions by wallet id: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,298 @@
+//! Write-ahead log for the sign-and-broadcast critical section.
+//!
+//! An intent row is committed before a transaction is signed and moved
+//! forward after each step, so a crash anywhere in between leaves enough on
+//! disk to tell whether the transaction could have reached the network.
+//! Every transition is its own transaction on a connection switched to
+//! `synchronous = FULL`; the pool otherwise runs WAL with `NORMAL`, which
+//! may lose the last commits on power failure.
+//!
+//! While an intent is not `released`, no other intent may claim the same
+//! (network, address, nonce).
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::pool::PoolConnection;
+use sqlx::{sqlite::SqlitePool, Connection, FromRow, Sqlite};
+
+/// Committed before signing; nothing can have been broadcast yet.
+pub const INTENT_SIGNING: &str = "signing";
+/// Signed; `tx_hash` is set but broadcast may not have happened.
+pub const INTENT_SIGNED: &str = "signed";
+/// `eth_sendRawTransaction` returned.
+pub const INTENT_BROADCAST: &str = "broadcast";
+/// Terminal: the transaction record exists.
+pub const INTENT_RECORDED: &str = "recorded";
+/// Terminal: never reached the network; the nonce may be used again.
+pub const INTENT_RELEASED: &str = "released";
+
+/// States that reconciliation has to resolve.
+pub const UNRESOLVED_INTENT_STATES: [&str; 3] = [INTENT_SIGNING, INTENT_SIGNED, INTENT_BROADCAST];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct SigningIntentRecord {
+    pub id: String,
+    pub wallet_name: String,
+    pub network: String,
+    /// Lowercase `0x` hex
+    pub from_address: String,
+    pub to_address: String,
+    /// Wei (decimal)
+    pub value: String,
+    pub nonce: i64,
+    /// Hash of the unsigned transaction (sighash)
+    pub canonical_hash: String,
+    /// Hash of the signed transaction; `None` until signed
+    pub tx_hash: Option<String>,
+    pub state: String,
+    /// Why the intent was released
+    pub error: Option<String>,
+    /// Unix seconds
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Redacted decision context, copied to `transaction_decisions` when the
+    /// transaction is recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<String>,
+}
+
+/// Fields of a new `signing_intents` row.
+#[derive(Debug, Clone)]
+pub struct NewSigningIntent<'a> {
+    pub id: &'a str,
+    pub wallet_name: &'a str,
+    pub network: &'a str,
+    pub from_address: &'a str,
+    pub to_address: &'a str,
+    pub value: &'a str,
+    pub nonce: i64,
+    pub canonical_hash: &'a str,
+    pub decision: Option<&'a str>,
+}
+
+const COLUMNS: &str = "id, wallet_name, network, from_address, to_address, value, nonce, \
+                       canonical_hash, tx_hash, state, error, created_at, updated_at, decision";
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS signing_intents (
+            id TEXT PRIMARY KEY,
+            wallet_name TEXT NOT NULL,
+            network TEXT NOT NULL,
+            from_address TEXT NOT NULL,
+            to_address TEXT NOT NULL,
+            value TEXT NOT NULL,
+            nonce INTEGER NOT NULL,
+            canonical_hash TEXT NOT NULL,
+            tx_hash TEXT,
+            state TEXT NOT NULL,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // released intents give their nonce back
+    sqlx::query(
+        r#"
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_signing_intents_nonce
+        ON signing_intents (network, from_address, nonce)
+        WHERE state != 'released'
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_signing_intents_state ON signing_intents (state, created_at)")
+        .execute(pool)
+        .await?;
+
+    // intents written before decision snapshots existed carry none
+    let has_decision: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('signing_intents') WHERE name = 'decision'",
+    )
+    .fetch_one(pool)
+    .await?;
+    if !has_decision {
+        sqlx::query("ALTER TABLE signing_intents ADD COLUMN decision TEXT")
+            .execute(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to add signing_intents.decision: {}", e))?;
+    }
+
+    Ok(())
+}
+
+async fn durable_connection(pool: &SqlitePool) -> Result<PoolConnection<Sqlite>> {
+    let mut conn = pool.acquire().await?;
+    sqlx::query("PRAGMA synchronous = FULL").execute(&mut *conn).await?;
+    Ok(conn)
+}
+
+async fn restore_connection(mut conn: PoolConnection<Sqlite>) -> Result<()> {
+    sqlx::query("PRAGMA synchronous = NORMAL").execute(&mut *conn).await?;
+    Ok(())
+}
+
+/// Commits a `signing` row. Returns `false` if the nonce already belongs to
+/// an intent that is not `released`.
+pub async fn begin(pool: &SqlitePool, intent: &NewSigningIntent<'_>, now: i64) -> Result<bool> {
+    let mut conn = durable_connection(pool).await?;
+    let mut tx = conn.begin().await?;
+    let result = sqlx::query(
+        r#"
+        INSERT INTO signing_intents (
+            id, wallet_name, network, from_address, to_address, value, nonce,
+            canonical_hash, state, created_at, updated_at, decision
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10, ?11)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(intent.id)
+    .bind(intent.wallet_name)
+    .bind(intent.network)
+    .bind(intent.from_address)
+    .bind(intent.to_address)
+    .bind(intent.value)
+    .bind(intent.nonce)
+    .bind(intent.canonical_hash)
+    .bind(INTENT_SIGNING)
+    .bind(now)
+    .bind(intent.decision)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to write signing intent: {}", e))?;
+    tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to commit signing intent: {}", e))?;
+    restore_connection(conn).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Moves `id` from `from` to `to`, optionally setting `tx_hash` / `error`.
+/// Returns `false` if the intent was no longer in `from` (e.g. reconciliation
+/// released it first).
+pub async fn transition(
+    pool: &SqlitePool,
+    id: &str,
+    from: &str,
+    to: &str,
+    tx_hash: Option<&str>,
+    error: Option<&str>,
+    now: i64,
+) -> Result<bool> {
+    let mut conn = durable_connection(pool).await?;
+    let mut tx = conn.begin().await?;
+    let result = sqlx::query(
+        r#"
+        UPDATE signing_intents
+        SET state = ?1, tx_hash = COALESCE(?2, tx_hash), error = COALESCE(?3, error), updated_at = ?4
+        WHERE id = ?5 AND state = ?6
+        "#,
+    )
+    .bind(to)
+    .bind(tx_hash)
+    .bind(error)
+    .bind(now)
+    .bind(id)
+    .bind(from)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to update signing intent {}: {}", id, e))?;
+    tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to commit signing intent {}: {}", id, e))?;
+    restore_connection(conn).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn get(pool: &SqlitePool, id: &str) -> Result<Option<SigningIntentRecord>> {
+    sqlx::query_as::<_, SigningIntentRecord>(&format!("SELECT {} FROM signing_intents WHERE id = ?1", COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load signing intent: {}", e))
+}
+
+/// Unresolved intents last touched at or before `updated_before`, oldest first.
+pub async fn list_unresolved(
+    pool: &SqlitePool,
+    updated_before: i64,
+    limit: i64,
+) -> Result<Vec<SigningIntentRecord>> {
+    sqlx::query_as::<_, SigningIntentRecord>(&format!(
+        "SELECT {} FROM signing_intents WHERE state IN (?1, ?2, ?3) AND updated_at <= ?4 \
+         ORDER BY created_at, rowid LIMIT ?5",
+        COLUMNS
+    ))
+    .bind(UNRESOLVED_INTENT_STATES[0])
+    .bind(UNRESOLVED_INTENT_STATES[1])
+    .bind(UNRESOLVED_INTENT_STATES[2])
+    .bind(updated_before)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load signing intents: {}", e))
+}
+
+/// Newest unresolved intent sending `value` wei from `wallet_name` to
+/// `to_address` that was created at or after `created_since`.
+pub async fn find_in_flight(
+    pool: &SqlitePool,
+    wallet_name: &str,
+    network: &str,
+    to_address: &str,
+    value: &str,
+    created_since: i64,
+) -> Result<Option<SigningIntentRecord>> {
+    sqlx::query_as::<_, SigningIntentRecord>(&format!(
+        "SELECT {} FROM signing_intents \
+         WHERE wallet_name = ?1 AND network = ?2 AND to_address = ?3 AND value = ?4 \
+         AND created_at >= ?5 AND state IN (?6, ?7, ?8) \
+         ORDER BY created_at DESC, rowid DESC LIMIT 1",
+        COLUMNS
+    ))
+    .bind(wallet_name)
+    .bind(network)
+    .bind(to_address)
+    .bind(value)
+    .bind(created_since)
+    .bind(UNRESOLVED_INTENT_STATES[0])
+    .bind(UNRESOLVED_INTENT_STATES[1])
+    .bind(UNRESOLVED_INTENT_STATES[2])
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to look up in-flight signing intents: {}", e))
+}
+
+/// The intent holding `nonce` of `from_address` on `network`, unless released.
+pub async fn find_by_nonce(
+    pool: &SqlitePool,
+    network: &str,
+    from_address: &str,
+    nonce: i64,
+) -> Result<Option<SigningIntentRecord>> {
+    sqlx::query_as::<_, SigningIntentRecord>(&format!(
+        "SELECT {} FROM signing_intents \
+         WHERE network = ?1 AND from_address = ?2 AND nonce = ?3 AND state != ?4",
+        COLUMNS
+    ))
+    .bind(network)
+    .bind(from_address)
+    .bind(nonce)
+    .bind(INTENT_RELEASED)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to look up signing intent by nonce: {}", e))
+}
+
+/// Whether `transactions` already has the row written for intent `id`.
+pub async fn transaction_recorded(pool: &SqlitePool, id: &str) -> Result<bool> {
+    let found: Option<i64> = sqlx::query_scalar("SELECT 1 FROM transactions WHERE id = ?1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to look up transaction {}: {}", id, e))?;
+    Ok(found.is_some())
+}
@@ -0,0 +1,284 @@
+//! Per wallet+network spending limits.
+//!
+//! `max_per_tx` caps a single send; `max_per_day` caps what the wallet sends
+//! on the network in any 24 hours, counting its `pending` and `confirmed`
+//! transactions. Both are kept in the network's smallest unit (wei) as
+//! decimal text: a `u128` does not fit an SQLite integer, and limits are
+//! compared as integers, never as floats.
+//!
+//! Transfers a history backfill found coming in are stored in `transactions`
+//! too; only the wallet's own sends count toward the daily cap.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::OwnedMutexGuard;
+
+use crate::core::amount::{Amount, AssetTag};
+use crate::storage::history_backfill::{DIRECTION_OUT, TX_ENTRY_INDEX};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendingLimitRecord {
+    pub wallet_name: String,
+    pub network: String,
+    /// Smallest unit; `None` when single sends are uncapped
+    pub max_per_tx: Option<u128>,
+    /// Smallest unit; `None` when there is no daily cap
+    pub max_per_day: Option<u128>,
+    pub updated_by: String,
+    /// Unix seconds
+    pub updated_at: i64,
+}
+
+/// Which limit a send would break, with the numbers it was compared against
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LimitExceeded {
+    #[error("amount {amount} is above the per-transaction limit of {limit}")]
+    PerTransaction { amount: u128, limit: u128 },
+    #[error("{spent} already sent in the last 24 hours; another {amount} would exceed the daily limit of {limit}")]
+    Daily { amount: u128, spent: u128, limit: u128 },
+}
+
+impl LimitExceeded {
+    pub fn code(&self) -> &'static str {
+        match self {
+            LimitExceeded::PerTransaction { .. } => "SPENDING_LIMIT_EXCEEDED",
+            LimitExceeded::Daily { .. } => "DAILY_SPENDING_LIMIT_EXCEEDED",
+        }
+    }
+}
+
+impl SpendingLimitRecord {
+    /// Checks a send of `amount` after `spent` went out in the last 24 hours.
+    /// Hitting a limit exactly is allowed.
+    pub fn check(&self, amount: u128, spent: u128) -> Result<(), LimitExceeded> {
+        if let Some(limit) = self.max_per_tx {
+            if amount > limit {
+                return Err(LimitExceeded::PerTransaction { amount, limit });
+            }
+        }
+        if let Some(limit) = self.max_per_day {
+            // an overflowing total is over any limit
+            if spent.checked_add(amount).is_none_or(|total| total > limit) {
+                return Err(LimitExceeded::Daily { amount, spent, limit });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(FromRow)]
+struct SpendingLimitRow {
+    wallet_name: String,
+    network: String,
+    max_per_tx: Option<String>,
+    max_per_day: Option<String>,
+    updated_by: String,
+    updated_at: i64,
+}
+
+impl TryFrom<SpendingLimitRow> for SpendingLimitRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SpendingLimitRow) -> Result<Self> {
+        let (wallet_name, network) = (&row.wallet_name, &row.network);
+        let parse = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::parse::<u128>)
+                .transpose()
+                .map_err(|e| anyhow::anyhow!("Malformed spending limit of {} on {}: {}", wallet_name, network, e))
+        };
+        Ok(Self {
+            max_per_tx: parse(&row.max_per_tx)?,
+            max_per_day: parse(&row.max_per_day)?,
+            wallet_name: row.wallet_name,
+            network: row.network,
+            updated_by: row.updated_by,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+const SELECT: &str =
+    "SELECT wallet_name, network, max_per_tx, max_per_day, updated_by, updated_at FROM spending_limits";
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS spending_limits (
+            wallet_name TEXT NOT NULL,
+            network TEXT NOT NULL,
+            max_per_tx TEXT,
+            max_per_day TEXT,
+            updated_by TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (wallet_name, network)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, wallet_name: &str, network: &str) -> Result<Option<SpendingLimitRecord>> {
+    let row: Option<SpendingLimitRow> = sqlx::query_as(&format!("{} WHERE wallet_name = ?1 AND network = ?2", SELECT))
+        .bind(wallet_name)
+        .bind(network)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load spending limit: {}", e))?;
+    row.map(SpendingLimitRecord::try_from).transpose()
+}
+
+pub async fn for_wallet(pool: &SqlitePool, wallet_name: &str) -> Result<Vec<SpendingLimitRecord>> {
+    let rows: Vec<SpendingLimitRow> = sqlx::query_as(&format!("{} WHERE wallet_name = ?1 ORDER BY network", SELECT))
+        .bind(wallet_name)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load spending limits: {}", e))?;
+    rows.into_iter().map(SpendingLimitRecord::try_from).collect()
+}
+
+pub async fn upsert(conn: &mut SqliteConnection, record: &SpendingLimitRecord) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO spending_limits (wallet_name, network, max_per_tx, max_per_day, updated_by, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ON CONFLICT(wallet_name, network) DO UPDATE SET
+            max_per_tx = excluded.max_per_tx,
+            max_per_day = excluded.max_per_day,
+            updated_by = excluded.updated_by,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&record.wallet_name)
+    .bind(&record.network)
+    .bind(record.max_per_tx.map(|v| v.to_string()))
+    .bind(record.max_per_day.map(|v| v.to_string()))
+    .bind(&record.updated_by)
+    .bind(record.updated_at)
+    .execute(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store spending limit: {}", e))?;
+    Ok(())
+}
+
+/// `false` if the wallet had no limit on `network`.
+pub async fn delete(conn: &mut SqliteConnection, wallet_name: &str, network: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM spending_limits WHERE wallet_name = ?1 AND network = ?2")
+        .bind(wallet_name)
+        .bind(network)
+        .execute(conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to delete spending limit: {}", e))?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_for_wallet(conn: &mut SqliteConnection, wallet_name: &str) -> Result<()> {
+    sqlx::query("DELETE FROM spending_limits WHERE wallet_name = ?1").bind(wallet_name).execute(&mut *conn).await?;
+    Ok(())
+}
+
+pub async fn rename(conn: &mut SqliteConnection, wallet_name: &str, new_name: &str) -> Result<()> {
+    sqlx::query("UPDATE spending_limits SET wallet_name = ?1 WHERE wallet_name = ?2")
+        .bind(new_name)
+        .bind(wallet_name)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// One lock per (wallet, network), held by a send from its limit check until
+/// its `transactions` row is written, so that two concurrent sends cannot both
+/// fit under what is left of the daily cap. Sends through other instances
+/// sharing the database are not covered.
+#[derive(Debug, Default)]
+pub struct SendLocks {
+    /// Keyed by (wallet, network)
+    locks: parking_lot::Mutex<HashMap<(String, String), SendLock>>,
+}
+
+type SendLock = Arc<tokio::sync::Mutex<()>>;
+
+impl SendLocks {
+    pub async fn lock(&self, wallet_name: &str, network: &str) -> OwnedMutexGuard<()> {
+        let lock = self.locks.lock().entry((wallet_name.to_string(), network.to_string())).or_default().clone();
+        lock.lock_owned().await
+    }
+}
+
+/// Total of the `pending` and `confirmed` sends of `wallet_name` on `network`
+/// created at or after `since`, in the smallest unit. Rows a backfill stored
+/// for a transfer the wallet did not send (incoming or to itself) are left
+/// out. Amounts are stored as decimal whole units and parsed exactly; one that
+/// cannot be read fails the sum rather than being skipped, so a bad row never
+/// loosens the limit.
+pub async fn sent_since(pool: &SqlitePool, wallet_name: &str, network: &str, since: DateTime<Utc>) -> Result<u128> {
+    let amounts: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT t.amount FROM transactions t
+        WHERE t.wallet_id = (SELECT id FROM wallets WHERE name = ?1) AND t.network = ?2 AND t.status IN ('pending', 'confirmed') AND t.created_at >= ?3
+          AND NOT EXISTS (
+              SELECT 1 FROM ledger_entries l
+              WHERE l.wallet_id = ?1 AND l.network = t.network AND l.tx_hash = t.tx_hash
+                AND l.log_index = ?4 AND l.direction <> ?5
+          )
+        "#,
+    )
+    .bind(wallet_name)
+    .bind(network)
+    .bind(since)
+    .bind(TX_ENTRY_INDEX)
+    .bind(DIRECTION_OUT)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to sum recent sends: {}", e))?;
+
+    amounts.iter().try_fold(0u128, |total, amount| {
+        // networks without a known native denomination are read with 18 decimals
+        let minimal = Amount::parse_native(network, amount)
+            .or_else(|_| Amount::parse(amount, 18, AssetTag::new(network, "")))
+            .map_err(|e| anyhow::anyhow!("Unreadable transaction amount {:?}: {}", amount, e))?
+            .minimal();
+        total.checked_add(minimal).ok_or_else(|| anyhow::anyhow!("Sent total overflows"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(max_per_tx: Option<u128>, max_per_day: Option<u128>) -> SpendingLimitRecord {
+        SpendingLimitRecord {
+            wallet_name: "treasury".to_string(),
+            network: "eth".to_string(),
+            max_per_tx,
+            max_per_day,
+            updated_by: "admin".to_string(),
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_exact_limits_pass() {
+        let limits = limit(Some(5), Some(10));
+        assert_eq!(limits.check(5, 5), Ok(()));
+        assert_eq!(limits.check(6, 0), Err(LimitExceeded::PerTransaction { amount: 6, limit: 5 }));
+        assert_eq!(limits.check(5, 6), Err(LimitExceeded::Daily { amount: 5, spent: 6, limit: 10 }));
+    }
+
+    #[test]
+    fn test_one_wei_over_the_daily_cap() {
+        let eth = 1_000_000_000_000_000_000u128;
+        let limits = limit(None, Some(3 * eth));
+        assert_eq!(limits.check(eth, 2 * eth), Ok(()));
+        assert!(matches!(limits.check(eth + 1, 2 * eth), Err(LimitExceeded::Daily { .. })));
+        // an overflowing total is refused, not wrapped
+        assert!(matches!(limits.check(u128::MAX, 1), Err(LimitExceeded::Daily { .. })));
+        assert_eq!(limit(None, None).check(u128::MAX, u128::MAX), Ok(()));
+    }
+}
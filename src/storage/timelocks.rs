@@ -0,0 +1,278 @@
+//! Time-locked transactions: signed at creation, sealed until released.
+//!
+//! A lock row keeps the sealed transaction, the server's share of its key
+//! and the fields the release re-checks the decrypted transaction against.
+//! It leaves `locked` exactly once; the last lock of an address to do so
+//! closes the address's [`FUTURE_LANE`] in the same transaction.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow, Sqlite, SqliteConnection, Transaction};
+
+use super::nonce_lanes::{self, FUTURE_LANE};
+
+/// Sealed, waiting for its unlock conditions and the custodian share
+pub const TIMELOCK_LOCKED: &str = "locked";
+/// Broadcast; `tx_hash` is set
+pub const TIMELOCK_RELEASED: &str = "released";
+/// Not released before `expires_at`; the transaction is never broadcast
+pub const TIMELOCK_EXPIRED: &str = "expired";
+/// Its nonce was used by another transaction, so it can never be mined
+pub const TIMELOCK_INVALIDATED: &str = "invalidated";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct TimelockRecord {
+    pub id: String,
+    pub wallet_name: String,
+    pub network: String,
+    /// Lowercase hex
+    pub from_address: String,
+    /// Lowercase hex
+    pub to_address: String,
+    /// Wei, decimal
+    pub value: String,
+    pub nonce: i64,
+    pub chain_id: i64,
+    pub gas_limit: i64,
+    /// Gas price (max fee per gas for EIP-1559), wei, decimal
+    pub gas_price: String,
+    /// Unix seconds; `None` when only a block is waited for
+    pub unlock_at: Option<i64>,
+    /// `None` when only a time is waited for
+    pub unlock_block: Option<i64>,
+    pub expires_at: i64,
+    pub status: String,
+    pub status_reason: Option<String>,
+    pub tx_hash: Option<String>,
+    pub created_at: i64,
+    pub finished_at: Option<i64>,
+    /// AES-GCM nonce and ciphertext of the signed transaction, hex
+    #[serde(skip)]
+    pub sealed_tx: String,
+    /// The server's Shamir share of the sealing key, hex
+    #[serde(skip)]
+    pub server_share: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NewTimelock<'a> {
+    pub id: &'a str,
+    pub wallet_name: &'a str,
+    pub network: &'a str,
+    pub from_address: &'a str,
+    pub to_address: &'a str,
+    pub value: &'a str,
+    pub nonce: i64,
+    pub chain_id: i64,
+    pub gas_limit: i64,
+    pub gas_price: &'a str,
+    pub unlock_at: Option<i64>,
+    pub unlock_block: Option<i64>,
+    pub expires_at: i64,
+    pub sealed_tx: &'a str,
+    pub server_share: &'a str,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS timelocks (
+            id TEXT PRIMARY KEY,
+            wallet_name TEXT NOT NULL,
+            network TEXT NOT NULL,
+            from_address TEXT NOT NULL,
+            to_address TEXT NOT NULL,
+            value TEXT NOT NULL,
+            nonce INTEGER NOT NULL,
+            chain_id INTEGER NOT NULL,
+            gas_limit INTEGER NOT NULL,
+            gas_price TEXT NOT NULL,
+            unlock_at INTEGER,
+            unlock_block INTEGER,
+            expires_at INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            status_reason TEXT,
+            tx_hash TEXT,
+            created_at INTEGER NOT NULL,
+            finished_at INTEGER,
+            sealed_tx TEXT NOT NULL,
+            server_share TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_timelocks_wallet ON timelocks (wallet_name, created_at)")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_timelocks_status ON timelocks (status, expires_at)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+const COLUMNS: &str = "id, wallet_name, network, from_address, to_address, value, nonce, chain_id, gas_limit, \
+     gas_price, unlock_at, unlock_block, expires_at, status, status_reason, tx_hash, created_at, finished_at, \
+     sealed_tx, server_share";
+
+pub async fn insert(pool: &SqlitePool, lock: &NewTimelock<'_>, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO timelocks (id, wallet_name, network, from_address, to_address, value, nonce, chain_id,
+                               gas_limit, gas_price, unlock_at, unlock_block, expires_at, status, created_at,
+                               sealed_tx, server_share)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+        "#,
+    )
+    .bind(lock.id)
+    .bind(lock.wallet_name)
+    .bind(lock.network)
+    .bind(lock.from_address.to_lowercase())
+    .bind(lock.to_address.to_lowercase())
+    .bind(lock.value)
+    .bind(lock.nonce)
+    .bind(lock.chain_id)
+    .bind(lock.gas_limit)
+    .bind(lock.gas_price)
+    .bind(lock.unlock_at)
+    .bind(lock.unlock_block)
+    .bind(lock.expires_at)
+    .bind(TIMELOCK_LOCKED)
+    .bind(now)
+    .bind(lock.sealed_tx)
+    .bind(lock.server_share)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store timelock {}: {}", lock.id, e))?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, id: &str) -> Result<Option<TimelockRecord>> {
+    let row = sqlx::query_as(&format!("SELECT {} FROM timelocks WHERE id = ?1", COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row)
+}
+
+/// Locks of `wallet_name`, newest first
+pub async fn list_for_wallet(pool: &SqlitePool, wallet_name: &str) -> Result<Vec<TimelockRecord>> {
+    let rows = sqlx::query_as(&format!(
+        "SELECT {} FROM timelocks WHERE wallet_name = ?1 ORDER BY created_at DESC, id",
+        COLUMNS
+    ))
+    .bind(wallet_name)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Closes the future lane of (network, address) unless a lock still holds
+/// one of its nonces.
+async fn close_lane_if_idle(tx: &mut Transaction<'_, Sqlite>, network: &str, address: &str) -> Result<()> {
+    let held: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM timelocks WHERE network = ?1 AND from_address = ?2 AND status = ?3")
+            .bind(network)
+            .bind(address)
+            .bind(TIMELOCK_LOCKED)
+            .fetch_one(&mut **tx)
+            .await?;
+    if held == 0 {
+        nonce_lanes::close(tx, network, address, FUTURE_LANE).await?;
+    }
+    Ok(())
+}
+
+/// Closes the future lane of (network, address) if no lock holds a nonce of
+/// it, e.g. after a lane nonce was taken for a lock that was never stored.
+pub async fn close_idle_lane(pool: &SqlitePool, network: &str, address: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    close_lane_if_idle(&mut tx, network, &address.to_lowercase()).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Moves a `locked` lock to `status`; `false`, writing nothing, if it was
+/// no longer locked.
+pub async fn finish(
+    pool: &SqlitePool,
+    id: &str,
+    status: &str,
+    reason: Option<&str>,
+    tx_hash: Option<&str>,
+    now: i64,
+) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+    let owner: Option<(String, String)> = sqlx::query_as(
+        r#"
+        UPDATE timelocks SET status = ?1, status_reason = ?2, tx_hash = ?3, finished_at = ?4
+        WHERE id = ?5 AND status = ?6
+        RETURNING network, from_address
+        "#,
+    )
+    .bind(status)
+    .bind(reason)
+    .bind(tx_hash)
+    .bind(now)
+    .bind(id)
+    .bind(TIMELOCK_LOCKED)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to update timelock {}: {}", id, e))?;
+    let Some((network, address)) = owner else {
+        return Ok(false);
+    };
+    close_lane_if_idle(&mut tx, &network, &address).await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Expires every lock past `expires_at` and returns them.
+pub async fn expire_overdue(pool: &SqlitePool, now: i64) -> Result<Vec<TimelockRecord>> {
+    let mut tx = pool.begin().await?;
+    let expired: Vec<TimelockRecord> = sqlx::query_as(&format!(
+        r#"
+        UPDATE timelocks SET status = ?1, status_reason = 'not released before expiry', finished_at = ?2
+        WHERE status = ?3 AND expires_at <= ?2
+        RETURNING {}
+        "#,
+        COLUMNS
+    ))
+    .bind(TIMELOCK_EXPIRED)
+    .bind(now)
+    .bind(TIMELOCK_LOCKED)
+    .fetch_all(&mut *tx)
+    .await?;
+    for lock in &expired {
+        close_lane_if_idle(&mut tx, &lock.network, &lock.from_address).await?;
+    }
+    tx.commit().await?;
+    Ok(expired)
+}
+
+/// Moves the locks of a renamed wallet.
+pub async fn rename(conn: &mut SqliteConnection, wallet_name: &str, new_name: &str) -> Result<()> {
+    sqlx::query("UPDATE timelocks SET wallet_name = ?1 WHERE wallet_name = ?2")
+        .bind(new_name)
+        .bind(wallet_name)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Drops the locks of a deleted wallet together with the lanes they held.
+pub async fn delete_for_wallet(conn: &mut SqliteConnection, wallet_name: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        DELETE FROM nonce_lanes WHERE lane = ?1 AND (network, address) IN
+            (SELECT network, from_address FROM timelocks WHERE wallet_name = ?2 AND status = ?3)
+        "#,
+    )
+    .bind(FUTURE_LANE)
+    .bind(wallet_name)
+    .bind(TIMELOCK_LOCKED)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query("DELETE FROM timelocks WHERE wallet_name = ?1").bind(wallet_name).execute(conn).await?;
+    Ok(())
+}
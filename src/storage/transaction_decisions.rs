@@ -0,0 +1,48 @@
+//! Decision context recorded with each server-signed transaction.
+//!
+//! The row is written in the same database transaction as the
+//! `transactions` row it belongs to (the primary keys are equal), so an
+//! auditor never finds a transaction without the limits, policies and
+//! rule-set hash that let it through, nor a snapshot for a transaction that
+//! was never recorded. Rows are never updated.
+
+use anyhow::Result;
+use sqlx::sqlite::SqlitePool;
+use sqlx::SqliteConnection;
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS transaction_decisions (
+            transaction_id TEXT PRIMARY KEY,
+            decision TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to create transaction_decisions table: {}", e))?;
+    Ok(())
+}
+
+/// Call inside the transaction that inserts the `transactions` row.
+pub async fn insert(conn: &mut SqliteConnection, transaction_id: &str, decision: &str, now: i64) -> Result<()> {
+    sqlx::query("INSERT INTO transaction_decisions (transaction_id, decision, created_at) VALUES (?1, ?2, ?3)")
+        .bind(transaction_id)
+        .bind(decision)
+        .bind(now)
+        .execute(conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to store decision of {}: {}", transaction_id, e))?;
+    Ok(())
+}
+
+/// The stored JSON text, if the transaction was recorded with one.
+pub async fn get(pool: &SqlitePool, transaction_id: &str) -> Result<Option<String>> {
+    let decision = sqlx::query_scalar("SELECT decision FROM transaction_decisions WHERE transaction_id = ?1")
+        .bind(transaction_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(decision)
+}
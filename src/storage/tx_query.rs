@@ -0,0 +1,232 @@
+//! Cross-wallet transaction queries for operations triage.
+//!
+//! Everything here reads the `transactions` table only; encrypted wallet
+//! blobs are never touched.
+
+use anyhow::Result;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection, SqlitePool};
+
+use super::TransactionRecord;
+
+/// Typed filter for [`super::WalletStorage::query_transactions`].
+/// Empty vectors / `None` fields do not constrain the query.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionFilter {
+    pub statuses: Vec<String>,
+    pub networks: Vec<String>,
+    pub wallet_ids: Vec<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub min_amount: Option<f64>,
+}
+
+impl TransactionFilter {
+    pub fn with_status(mut self, status: &str) -> Self {
+        self.statuses.push(status.to_string());
+        self
+    }
+
+    pub fn with_network(mut self, network: &str) -> Self {
+        self.networks.push(network.to_string());
+        self
+    }
+
+    pub fn older_than(mut self, age: chrono::Duration) -> Self {
+        self.created_before = Some(Utc::now() - age);
+        self
+    }
+}
+
+/// Adds the receipt columns filled in by
+/// [`super::WalletStorage::finalize_transaction`] to tables created before
+/// them; older rows keep `NULL` there.
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    for (column, ty) in [("fee_actual", "TEXT"), ("block_number", "INTEGER")] {
+        let has_column: bool =
+            sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_table_info('transactions') WHERE name = ?")
+                .bind(column)
+                .fetch_one(pool)
+                .await?;
+        if !has_column {
+            sqlx::query(&format!("ALTER TABLE transactions ADD COLUMN {} {}", column, ty))
+                .execute(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to add transactions.{}: {}", column, e))?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn init_indexes(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_transactions_status_created ON transactions (status, created_at)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_transactions_network_status ON transactions (network, status)",
+    )
+    .execute(pool)
+    .await?;
+
+    // Per-wallet newest-first scans merged by `wallet_set_page`
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_transactions_wallet_created ON transactions (wallet_id, created_at, id)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+fn push_in_list<'a>(qb: &mut QueryBuilder<'a, Sqlite>, column: &str, values: &'a [String]) {
+    qb.push(format!(" AND {} IN (", column));
+    let mut sep = qb.separated(", ");
+    for v in values {
+        sep.push_bind(v.as_str());
+    }
+    sep.push_unseparated(")");
+}
+
+fn push_where<'a>(qb: &mut QueryBuilder<'a, Sqlite>, filter: &'a TransactionFilter) {
+    qb.push(" WHERE 1 = 1");
+    if !filter.statuses.is_empty() {
+        push_in_list(qb, "status", &filter.statuses);
+    }
+    if !filter.networks.is_empty() {
+        push_in_list(qb, "network", &filter.networks);
+    }
+    if !filter.wallet_ids.is_empty() {
+        push_in_list(qb, "wallet_id", &filter.wallet_ids);
+    }
+    if let Some(after) = filter.created_after {
+        qb.push(" AND created_at >= ").push_bind(after);
+    }
+    if let Some(before) = filter.created_before {
+        qb.push(" AND created_at < ").push_bind(before);
+    }
+    if let Some(min) = filter.min_amount {
+        qb.push(" AND CAST(amount AS REAL) >= ").push_bind(min);
+    }
+}
+
+/// Returns one page of matching rows (oldest first) plus the total match count.
+pub async fn query(
+    conn: &mut SqliteConnection,
+    filter: &TransactionFilter,
+    offset: usize,
+    limit: usize,
+) -> Result<(Vec<TransactionRecord>, usize)> {
+    let mut count_qb: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT COUNT(*) FROM transactions");
+    push_where(&mut count_qb, filter);
+    let total: i64 = count_qb
+        .build_query_scalar()
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to count transactions: {}", e))?;
+
+    let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+        "SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, created_at, confirmed_at, \
+         integrity_hash, fee_actual, block_number FROM transactions",
+    );
+    push_where(&mut qb, filter);
+    qb.push(" ORDER BY created_at ASC LIMIT ")
+        .push_bind(limit as i64)
+        .push(" OFFSET ")
+        .push_bind(offset as i64);
+
+    let rows = qb
+        .build_query_as::<TransactionRecord>()
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to query transactions: {}", e))?;
+
+    Ok((rows, total.max(0) as usize))
+}
+
+/// Position after the last row of a [`wallet_set_page`] page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: String,
+}
+
+impl TransactionCursor {
+    /// Opaque URL-safe token handed to API clients.
+    pub fn encode(&self) -> String {
+        let raw = format!("{}|{}", self.created_at.to_rfc3339(), self.id);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(token: &str) -> Result<Self> {
+        let invalid = || anyhow::anyhow!("Invalid transaction cursor");
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(token.trim()).map_err(|_| invalid())?;
+        let raw = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (ts, id) = raw.split_once('|').ok_or_else(invalid)?;
+        let created_at = DateTime::parse_from_rfc3339(ts).map_err(|_| invalid())?.with_timezone(&Utc);
+        Ok(Self { created_at, id: id.to_string() })
+    }
+}
+
+/// Newest-first page of the transactions of several wallets, merged.
+///
+/// Each wallet contributes at most `limit + 1` rows from its own
+/// `(wallet_id, created_at, id)` index range, and only those are merged and
+/// sorted, so the cost grows with the number of wallets and the page size,
+/// not with the wallets' total history.
+pub async fn wallet_set_page(
+    pool: &SqlitePool,
+    wallet_ids: &[String],
+    cursor: Option<&TransactionCursor>,
+    limit: usize,
+) -> Result<(Vec<TransactionRecord>, Option<TransactionCursor>)> {
+    if wallet_ids.is_empty() || limit == 0 {
+        return Ok((Vec::new(), None));
+    }
+    // One extra row tells whether another page exists.
+    let fetch = (limit + 1) as i64;
+
+    let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("");
+    for (i, wallet_id) in wallet_ids.iter().enumerate() {
+        if i > 0 {
+            qb.push(" UNION ALL ");
+        }
+        qb.push(
+            "SELECT * FROM (SELECT id, wallet_id, tx_hash, network, from_address, to_address, amount, fee, status, \
+             created_at, confirmed_at, integrity_hash, fee_actual, block_number FROM transactions WHERE wallet_id = ",
+        )
+        .push_bind(wallet_id.as_str());
+        if let Some(c) = cursor {
+            qb.push(" AND (created_at, id) < (").push_bind(c.created_at).push(", ").push_bind(c.id.as_str()).push(")");
+        }
+        qb.push(" ORDER BY created_at DESC, id DESC LIMIT ").push_bind(fetch).push(")");
+    }
+    qb.push(" ORDER BY created_at DESC, id DESC LIMIT ").push_bind(fetch);
+
+    let mut rows = qb
+        .build_query_as::<TransactionRecord>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to query wallet set transactions: {}", e))?;
+
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+    let next = if has_more {
+        rows.last().map(|t| TransactionCursor { created_at: t.created_at, id: t.id.clone() })
+    } else {
+        None
+    };
+    Ok((rows, next))
+}
+
+/// `EXPLAIN QUERY PLAN` detail lines for the page query built from `filter`.
+pub async fn explain(pool: &SqlitePool, filter: &TransactionFilter) -> Result<Vec<String>> {
+    let mut qb: QueryBuilder<Sqlite> =
+        QueryBuilder::new("EXPLAIN QUERY PLAN SELECT id FROM transactions");
+    push_where(&mut qb, filter);
+    let rows: Vec<(i64, i64, i64, String)> = qb.build_query_as().fetch_all(pool).await?;
+    Ok(rows.into_iter().map(|r| r.3).collect())
+}
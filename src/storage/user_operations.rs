@@ -0,0 +1,149 @@
+//! ERC-4337 UserOperations submitted to a bundler.
+//!
+//! A row is written once the bundler accepted the op and stays `pending`
+//! until its receipt shows up. Inclusion moves it to `included` or `reverted`
+//! in the same transaction that records the bundle as a `TransactionRecord`,
+//! and only a `pending` row can move, so concurrent pollers record it once.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+/// Accepted by the bundler, not on chain yet
+pub const USER_OP_PENDING: &str = "pending";
+/// Included and the account call succeeded
+pub const USER_OP_INCLUDED: &str = "included";
+/// Included, but the account's `execute` reverted (gas was still paid)
+pub const USER_OP_REVERTED: &str = "reverted";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct UserOperationRecord {
+    pub user_op_hash: String,
+    pub wallet_name: String,
+    pub network: String,
+    /// Smart account address (lowercase hex)
+    pub sender: String,
+    pub entry_point: String,
+    /// Destination of `execute`
+    pub to_address: String,
+    /// Wei, decimal
+    pub value: String,
+    pub nonce: String,
+    pub status: String,
+    /// Bundle transaction, once included
+    pub tx_hash: Option<String>,
+    /// Wei, decimal, once included
+    pub actual_gas_cost: Option<String>,
+    /// Revert reason reported by the bundler
+    pub error: Option<String>,
+    /// Unix seconds
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Fields of a new `user_operations` row.
+#[derive(Debug, Clone)]
+pub struct NewUserOperation<'a> {
+    pub user_op_hash: &'a str,
+    pub wallet_name: &'a str,
+    pub network: &'a str,
+    pub sender: &'a str,
+    pub entry_point: &'a str,
+    pub to_address: &'a str,
+    pub value: &'a str,
+    pub nonce: &'a str,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS user_operations (
+            user_op_hash TEXT PRIMARY KEY,
+            wallet_name TEXT NOT NULL,
+            network TEXT NOT NULL,
+            sender TEXT NOT NULL,
+            entry_point TEXT NOT NULL,
+            to_address TEXT NOT NULL,
+            value TEXT NOT NULL,
+            nonce TEXT NOT NULL,
+            status TEXT NOT NULL,
+            tx_hash TEXT,
+            actual_gas_cost TEXT,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_user_operations_status ON user_operations (status, created_at)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn insert(pool: &SqlitePool, op: &NewUserOperation<'_>, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_operations
+            (user_op_hash, wallet_name, network, sender, entry_point, to_address, value, nonce, status, created_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10)
+        "#,
+    )
+    .bind(op.user_op_hash)
+    .bind(op.wallet_name)
+    .bind(op.network)
+    .bind(op.sender)
+    .bind(op.entry_point)
+    .bind(op.to_address)
+    .bind(op.value)
+    .bind(op.nonce)
+    .bind(USER_OP_PENDING)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record user operation: {}", e))?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, user_op_hash: &str) -> Result<Option<UserOperationRecord>> {
+    Ok(sqlx::query_as::<_, UserOperationRecord>(
+        "SELECT user_op_hash, wallet_name, network, sender, entry_point, to_address, value, nonce, \
+         status, tx_hash, actual_gas_cost, error, created_at, updated_at \
+         FROM user_operations WHERE user_op_hash = ?1",
+    )
+    .bind(user_op_hash)
+    .fetch_optional(pool)
+    .await?)
+}
+
+/// Moves a `pending` op to `status`; `false` if it was no longer pending.
+pub async fn mark_included(
+    conn: &mut SqliteConnection,
+    user_op_hash: &str,
+    status: &str,
+    tx_hash: &str,
+    actual_gas_cost: &str,
+    error: Option<&str>,
+    now: i64,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE user_operations
+        SET status = ?1, tx_hash = ?2, actual_gas_cost = ?3, error = ?4, updated_at = ?5
+        WHERE user_op_hash = ?6 AND status = ?7
+        "#,
+    )
+    .bind(status)
+    .bind(tx_hash)
+    .bind(actual_gas_cost)
+    .bind(error)
+    .bind(now)
+    .bind(user_op_hash)
+    .bind(USER_OP_PENDING)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to update user operation {}: {}", user_op_hash, e))?;
+    Ok(result.rows_affected() == 1)
+}
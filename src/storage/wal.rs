@@ -0,0 +1,120 @@
+//! Write-ahead log management and database file statistics.
+//!
+//! SQLite only checkpoints automatically from the connection that commits,
+//! and only in PASSIVE mode, which never shrinks the `-wal` file and gives up
+//! whenever a reader holds an old snapshot. Explicit `TRUNCATE` checkpoints
+//! bound the file; `journal_size_limit` caps what is left behind when a
+//! checkpoint cannot reset it.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::pool::PoolConnection;
+use sqlx::sqlite::{Sqlite, SqliteConnectOptions, SqliteConnection, SqlitePool};
+
+/// Per-connection WAL pragmas applied when the pool opens a connection;
+/// `None` keeps SQLite's default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalSettings {
+    /// `PRAGMA journal_size_limit` in bytes
+    pub journal_size_limit: Option<i64>,
+    /// `PRAGMA wal_autocheckpoint` in pages
+    pub wal_autocheckpoint: Option<u32>,
+}
+
+impl WalSettings {
+    pub(super) fn apply(&self, options: SqliteConnectOptions) -> SqliteConnectOptions {
+        let options = match self.journal_size_limit {
+            Some(bytes) => options.pragma("journal_size_limit", bytes.to_string()),
+            None => options,
+        };
+        match self.wal_autocheckpoint {
+            Some(pages) => options.pragma("wal_autocheckpoint", pages.to_string()),
+            None => options,
+        }
+    }
+}
+
+/// Result row of `PRAGMA wal_checkpoint`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CheckpointOutcome {
+    /// A reader or writer prevented the checkpoint from completing
+    pub busy: bool,
+    /// Frames left in the WAL; -1 when not in WAL mode. A completed
+    /// `TRUNCATE` resets the WAL header first, so it reports 0 here.
+    pub log_frames: i64,
+    /// Frames copied back into the database file (same caveat)
+    pub checkpointed_frames: i64,
+}
+
+/// On-disk size of the database, sampled with `PRAGMA` queries
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DatabaseFileStats {
+    /// Empty for in-memory databases
+    pub path: String,
+    pub db_bytes: u64,
+    /// Size of the `-wal` file; 0 when absent
+    pub wal_bytes: u64,
+    pub page_count: i64,
+    pub page_size: i64,
+    /// Unused pages a `VACUUM` would return to the filesystem
+    pub freelist_count: i64,
+}
+
+/// A writer connection held for one checkpoint cycle. The sizes sampled
+/// before and after and the checkpoint itself all run on it, so the cycle
+/// leaves no connection of its own on the way back to the pool while the
+/// busy writers are counted.
+pub struct WalSession {
+    conn: PoolConnection<Sqlite>,
+    others_in_use: usize,
+}
+
+impl WalSession {
+    pub(super) async fn acquire(pool: &SqlitePool) -> Result<Self> {
+        let conn = pool.acquire().await?;
+        let others_in_use = (pool.size() as usize).saturating_sub(pool.num_idle() + 1);
+        Ok(Self { conn, others_in_use })
+    }
+
+    /// Writer connections checked out by others when the session started
+    pub fn others_in_use(&self) -> usize {
+        self.others_in_use
+    }
+
+    pub async fn file_stats(&mut self) -> Result<DatabaseFileStats> {
+        file_stats(&mut self.conn).await
+    }
+
+    /// `PRAGMA wal_checkpoint(TRUNCATE)`; waits for readers up to the busy
+    /// timeout, then reports `busy` instead of failing.
+    pub async fn checkpoint_truncate(&mut self) -> Result<CheckpointOutcome> {
+        checkpoint_truncate(&mut self.conn).await
+    }
+}
+
+async fn checkpoint_truncate(conn: &mut SqliteConnection) -> Result<CheckpointOutcome> {
+    let (busy, log_frames, checkpointed_frames): (i64, i64, i64) =
+        sqlx::query_as("PRAGMA wal_checkpoint(TRUNCATE)")
+            .fetch_one(conn)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to checkpoint WAL: {}", e))?;
+    Ok(CheckpointOutcome { busy: busy != 0, log_frames, checkpointed_frames })
+}
+
+pub(super) async fn file_stats(conn: &mut SqliteConnection) -> Result<DatabaseFileStats> {
+    let path: String = sqlx::query_scalar("SELECT file FROM pragma_database_list WHERE name = 'main'")
+        .fetch_optional(&mut *conn)
+        .await?
+        .unwrap_or_default();
+    let page_count: i64 = sqlx::query_scalar("PRAGMA page_count").fetch_one(&mut *conn).await?;
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size").fetch_one(&mut *conn).await?;
+    let freelist_count: i64 = sqlx::query_scalar("PRAGMA freelist_count").fetch_one(&mut *conn).await?;
+
+    let size_of = |file: &str| std::fs::metadata(file).map(|m| m.len()).unwrap_or(0);
+    let (db_bytes, wal_bytes) = if path.is_empty() {
+        ((page_count * page_size).max(0) as u64, 0)
+    } else {
+        (size_of(&path), size_of(&format!("{}-wal", path)))
+    };
+    Ok(DatabaseFileStats { path, db_bytes, wal_bytes, page_count, page_size, freelist_count })
+}
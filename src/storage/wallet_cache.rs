@@ -0,0 +1,162 @@
+//! In-process cache of wallet metadata (the full list and lookups by name).
+//!
+//! Coherence with other instances comes from the `wallets` cache epoch: before
+//! serving an entry the owner reads the epoch, at most once per
+//! `epoch_check_interval`, and a value different from the last one seen drops
+//! every entry. Entries also expire after `ttl`, which is the only bound on
+//! staleness while the epoch cannot be read. Mutations made through the owning
+//! storage handle flush the cache directly.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::WalletMetadata;
+
+/// Name lookups kept at most; the map is dropped wholesale when it fills up
+const MAX_NAME_ENTRIES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct WalletCacheSettings {
+    /// Maximum age of a served entry, epoch or not
+    pub ttl: Duration,
+    /// Minimum spacing between two epoch reads
+    pub epoch_check_interval: Duration,
+}
+
+impl Default for WalletCacheSettings {
+    fn default() -> Self {
+        Self { ttl: Duration::from_secs(30), epoch_check_interval: Duration::from_secs(1) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Flushes caused by an epoch change made elsewhere
+    pub flushes: u64,
+    pub epoch_read_failures: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    /// Last epoch read; `None` until the first read or after a local flush
+    epoch: Option<i64>,
+    checked_at: Option<DateTime<Utc>>,
+    list: Option<(DateTime<Utc>, Arc<Vec<WalletMetadata>>)>,
+    /// Misses are cached too (`None`), so polling an unknown name stays cheap
+    by_name: HashMap<String, (DateTime<Utc>, Option<WalletMetadata>)>,
+    /// Bumped by every flush; a load that started before one is not stored
+    generation: u64,
+    stats: WalletCacheStats,
+}
+
+impl CacheState {
+    fn clear(&mut self) {
+        self.list = None;
+        self.by_name.clear();
+        self.generation += 1;
+    }
+}
+
+#[derive(Debug)]
+pub struct WalletCache {
+    settings: WalletCacheSettings,
+    state: Mutex<CacheState>,
+}
+
+impl WalletCache {
+    pub fn new(settings: WalletCacheSettings) -> Self {
+        Self { settings, state: Mutex::new(CacheState::default()) }
+    }
+
+    fn fresh(&self, fetched_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        (now - fetched_at).to_std().map(|age| age < self.settings.ttl).unwrap_or(true)
+    }
+
+    pub fn epoch_check_due(&self, now: DateTime<Utc>) -> bool {
+        match self.state.lock().checked_at {
+            Some(at) => (now - at).to_std().map(|gap| gap >= self.settings.epoch_check_interval).unwrap_or(false),
+            None => true,
+        }
+    }
+
+    /// Records an epoch read at `now`, dropping every entry if it moved.
+    pub fn observe_epoch(&self, epoch: i64, now: DateTime<Utc>) {
+        let mut state = self.state.lock();
+        if state.epoch.is_some_and(|seen| seen != epoch) {
+            state.clear();
+            state.stats.flushes += 1;
+        }
+        state.epoch = Some(epoch);
+        state.checked_at = Some(now);
+    }
+
+    /// Entries stay servable until their TTL; the next read retries after the
+    /// usual interval rather than on every request.
+    pub fn epoch_read_failed(&self, now: DateTime<Utc>) {
+        let mut state = self.state.lock();
+        state.checked_at = Some(now);
+        state.stats.epoch_read_failures += 1;
+    }
+
+    /// Drops every entry after a mutation made through this handle. The epoch
+    /// is forgotten so the bump that mutation made is not counted as foreign.
+    pub fn flush(&self) {
+        let mut state = self.state.lock();
+        state.clear();
+        state.epoch = None;
+        state.checked_at = None;
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.state.lock().generation
+    }
+
+    pub fn list(&self, now: DateTime<Utc>) -> Option<Arc<Vec<WalletMetadata>>> {
+        let mut state = self.state.lock();
+        let cached = state.list.as_ref().filter(|(at, _)| self.fresh(*at, now)).map(|(_, list)| list.clone());
+        match cached {
+            Some(_) => state.stats.hits += 1,
+            None => state.stats.misses += 1,
+        }
+        cached
+    }
+
+    pub fn put_list(&self, list: Arc<Vec<WalletMetadata>>, now: DateTime<Utc>, generation: u64) {
+        let mut state = self.state.lock();
+        if state.generation == generation {
+            state.list = Some((now, list));
+        }
+    }
+
+    /// `Some(None)` is a cached "no such wallet".
+    pub fn wallet(&self, name: &str, now: DateTime<Utc>) -> Option<Option<WalletMetadata>> {
+        let mut state = self.state.lock();
+        let cached = state.by_name.get(name).filter(|(at, _)| self.fresh(*at, now)).map(|(_, w)| w.clone());
+        match cached {
+            Some(_) => state.stats.hits += 1,
+            None => state.stats.misses += 1,
+        }
+        cached
+    }
+
+    pub fn put_wallet(&self, name: &str, wallet: Option<WalletMetadata>, now: DateTime<Utc>, generation: u64) {
+        let mut state = self.state.lock();
+        if state.generation == generation {
+            if state.by_name.len() >= MAX_NAME_ENTRIES {
+                state.by_name.clear();
+            }
+            state.by_name.insert(name.to_string(), (now, wallet));
+        }
+    }
+
+    pub fn stats(&self) -> WalletCacheStats {
+        self.state.lock().stats
+    }
+}
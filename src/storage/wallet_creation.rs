@@ -0,0 +1,223 @@
+//! Crash-safe wallet creation.
+//!
+//! Every row a new wallet needs (the `wallets` row, its network rows, the
+//! journal event and the HMAC'd audit entry) is written in one transaction.
+//! The wallet row goes in with `creation_state = 'pending'` and flips to
+//! `complete` as the transaction's last statement, so reads that filter on
+//! `complete` never see a wallet whose auxiliary rows are missing.
+//!
+//! A pending row that did get committed (a build that wrote the steps
+//! separately, or a storage fault mid-way) is found by [`pending_older_than`]
+//! on startup. It is finalized when its blob decodes to the wallet's sealed
+//! key material and every row the creation promised exists; otherwise the
+//! blob is overwritten with zeros and the wallet is removed.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, types::chrono::NaiveDateTime, FromRow, SqliteConnection};
+
+use crate::core::wallet_info::SecureWalletData;
+
+pub const CREATION_PENDING: &str = "pending";
+pub const CREATION_COMPLETE: &str = "complete";
+
+/// AES-256-GCM appends a 16-byte tag to the sealed master key
+const GCM_TAG_LEN: usize = 16;
+const GCM_NONCE_LEN: usize = 12;
+
+/// Point in the creation transaction after which a fault is injected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreationStep {
+    WalletRow,
+    Networks,
+    Journal,
+    Audit,
+}
+
+/// Storage fault injected into wallet creation (tests only)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreationFault {
+    /// The statement after the step fails and the transaction rolls back.
+    Error(CreationStep),
+    /// The process dies after the step with everything written so far
+    /// durable, as a non-atomic writer would leave it.
+    Crash(CreationStep),
+}
+
+impl CreationFault {
+    pub fn step(self) -> CreationStep {
+        match self {
+            CreationFault::Error(step) | CreationFault::Crash(step) => step,
+        }
+    }
+}
+
+/// A wallet row still in `pending` state
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingWallet {
+    pub id: String,
+    pub name: String,
+    pub encrypted_data: Vec<u8>,
+}
+
+/// What reconciliation did with one pending wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PendingWalletDecision {
+    pub wallet_id: String,
+    pub name: String,
+    /// `finalized` or `discarded`
+    pub action: &'static str,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PendingWalletReport {
+    pub checked: usize,
+    pub finalized: usize,
+    pub discarded: usize,
+    pub decisions: Vec<PendingWalletDecision>,
+}
+
+/// Adds `creation_state` to `wallets` tables created before it existed;
+/// those rows were all written by a completed creation.
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    let has_column: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('wallets') WHERE name = 'creation_state'",
+    )
+    .fetch_one(pool)
+    .await?;
+    if !has_column {
+        sqlx::query("ALTER TABLE wallets ADD COLUMN creation_state TEXT NOT NULL DEFAULT 'complete'")
+            .execute(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to add wallets.creation_state: {}", e))?;
+    }
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_wallets_creation_state ON wallets (creation_state, created_at)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Pending wallets created at or before `cutoff`, oldest first
+pub async fn pending_older_than(pool: &SqlitePool, cutoff: NaiveDateTime) -> Result<Vec<PendingWallet>> {
+    sqlx::query_as::<_, PendingWallet>(
+        "SELECT id, name, encrypted_data FROM wallets \
+         WHERE creation_state = ?1 AND created_at <= ?2 ORDER BY created_at, id",
+    )
+    .bind(CREATION_PENDING)
+    .bind(cutoff)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to list pending wallets: {}", e))
+}
+
+/// Id of the pending wallet holding `name`, if any.
+///
+/// Read inside the creation transaction, which is opened with
+/// `BEGIN IMMEDIATE`: a deferred transaction that reads first cannot upgrade
+/// once another creation commits and fails with SQLITE_BUSY.
+pub async fn pending_id(conn: &mut SqliteConnection, name: &str) -> Result<Option<String>> {
+    Ok(sqlx::query_scalar("SELECT id FROM wallets WHERE name = ?1 AND creation_state = ?2")
+        .bind(name)
+        .bind(CREATION_PENDING)
+        .fetch_optional(&mut *conn)
+        .await?)
+}
+
+/// Why the blob is not usable key material for `name`, if it is not.
+///
+/// The master key is sealed under the user's password, which is not
+/// available here; the check is that the blob decodes and carries a
+/// well-formed AES-GCM ciphertext, salt and nonce for this wallet.
+pub fn blob_defect(name: &str, blob: &[u8]) -> Option<String> {
+    let data: SecureWalletData = match bincode::deserialize(blob) {
+        Ok(data) => data,
+        Err(e) => return Some(format!("blob does not decode: {}", e)),
+    };
+    if data.info.name != name {
+        return Some(format!("blob belongs to wallet {:?}", data.info.name));
+    }
+    if data.encrypted_master_key.len() <= GCM_TAG_LEN || data.salt.is_empty() || data.nonce.len() != GCM_NONCE_LEN {
+        return Some("sealed master key is truncated".to_string());
+    }
+    None
+}
+
+/// Rows the creation transaction of `wallet_id` was required to write that
+/// are missing: the journal event, the audit entry with its MAC, and one
+/// network row per network the journal event lists.
+pub async fn missing_rows(conn: &mut SqliteConnection, wallet_id: &str, name: &str) -> Result<Vec<String>> {
+    let mut missing = Vec::new();
+    let payload: Option<String> = sqlx::query_scalar(
+        "SELECT payload FROM events_journal WHERE event_type = ?1 AND entity_id = ?2 ORDER BY seq LIMIT 1",
+    )
+    .bind(super::events_journal::WALLET_CREATED)
+    .bind(wallet_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    match payload {
+        None => missing.push("journal event".to_string()),
+        Some(payload) => {
+            let payload: serde_json::Value = serde_json::from_str(&payload).unwrap_or_default();
+            let networks = payload["networks"].as_array().cloned().unwrap_or_default();
+            for network in networks.iter().filter_map(|n| n["network"].as_str()) {
+                let present: bool = sqlx::query_scalar(
+                    "SELECT COUNT(*) > 0 FROM wallet_networks WHERE wallet_name = ?1 AND network = ?2",
+                )
+                .bind(name)
+                .bind(network)
+                .fetch_one(&mut *conn)
+                .await?;
+                if !present {
+                    missing.push(format!("network {}", network));
+                }
+            }
+        }
+    }
+    let audited: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM audit_logs a JOIN audit_logs_hmac h ON h.audit_id = a.id \
+         WHERE a.wallet_id = ?1 AND a.action = 'wallet_created'",
+    )
+    .bind(wallet_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if !audited {
+        missing.push("audit entry".to_string());
+    }
+    Ok(missing)
+}
+
+/// Marks the wallet complete; `false` if it was not pending
+pub async fn finalize(conn: &mut SqliteConnection, wallet_id: &str) -> Result<bool> {
+    let result = sqlx::query("UPDATE wallets SET creation_state = ?1 WHERE id = ?2 AND creation_state = ?3")
+        .bind(CREATION_COMPLETE)
+        .bind(wallet_id)
+        .bind(CREATION_PENDING)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to finalize wallet: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Zeroes the pending wallet's blob in place, then deletes the row and the
+/// auxiliary rows keyed by its name. `false` if it was not pending.
+pub async fn discard(conn: &mut SqliteConnection, wallet_id: &str, name: &str) -> Result<bool> {
+    let overwritten = sqlx::query(
+        "UPDATE wallets SET encrypted_data = zeroblob(length(encrypted_data)) WHERE id = ?1 AND creation_state = ?2",
+    )
+    .bind(wallet_id)
+    .bind(CREATION_PENDING)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to overwrite wallet blob: {}", e))?;
+    if overwritten.rows_affected() == 0 {
+        return Ok(false);
+    }
+    sqlx::query("DELETE FROM wallets WHERE id = ?1")
+        .bind(wallet_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to delete pending wallet: {}", e))?;
+    super::wallet_networks::delete_for_wallet(conn, name).await?;
+    Ok(true)
+}
@@ -0,0 +1,163 @@
+//! Named wallet groups ("ops-eu", "cold-staging") for reporting and batch
+//! operations.
+//!
+//! A group is owned by one user and lists wallet names; a wallet can be in
+//! any number of groups. Deleting a group drops its membership rows only,
+//! and deleting a wallet drops the wallet's memberships.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct WalletGroupRecord {
+    pub name: String,
+    pub owner_user_id: String,
+    /// Owner user id, or `admin`
+    pub created_by: String,
+    /// Unix seconds
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct WalletGroupMember {
+    pub wallet_name: String,
+    pub added_by: String,
+    /// Unix seconds
+    pub added_at: i64,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS wallet_groups (
+            name TEXT PRIMARY KEY,
+            owner_user_id TEXT NOT NULL,
+            created_by TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS wallet_group_members (
+            group_name TEXT NOT NULL,
+            wallet_name TEXT NOT NULL,
+            added_by TEXT NOT NULL,
+            added_at INTEGER NOT NULL,
+            PRIMARY KEY (group_name, wallet_name)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    // 删除wallet时按wallet名清理成员关系
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_wallet_group_members_wallet ON wallet_group_members (wallet_name)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// `false` if a group of that name already exists.
+pub async fn insert(conn: &mut SqliteConnection, record: &WalletGroupRecord) -> Result<bool> {
+    let result = sqlx::query(
+        "INSERT INTO wallet_groups (name, owner_user_id, created_by, created_at) VALUES (?1, ?2, ?3, ?4) \
+         ON CONFLICT(name) DO NOTHING",
+    )
+    .bind(&record.name)
+    .bind(&record.owner_user_id)
+    .bind(&record.created_by)
+    .bind(record.created_at)
+    .execute(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to create wallet group: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn get(pool: &SqlitePool, name: &str) -> Result<Option<WalletGroupRecord>> {
+    let row = sqlx::query_as::<_, WalletGroupRecord>(
+        "SELECT name, owner_user_id, created_by, created_at FROM wallet_groups WHERE name = ?1",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Ordered by name; `owner` filters when set.
+pub async fn list(pool: &SqlitePool, owner: Option<&str>) -> Result<Vec<WalletGroupRecord>> {
+    let rows = sqlx::query_as::<_, WalletGroupRecord>(
+        "SELECT name, owner_user_id, created_by, created_at FROM wallet_groups \
+         WHERE (?1 IS NULL OR owner_user_id = ?1) ORDER BY name",
+    )
+    .bind(owner)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Removes the group and its membership rows; the wallets are untouched.
+pub async fn delete(conn: &mut SqliteConnection, name: &str) -> Result<bool> {
+    sqlx::query("DELETE FROM wallet_group_members WHERE group_name = ?1").bind(name).execute(&mut *conn).await?;
+    let result = sqlx::query("DELETE FROM wallet_groups WHERE name = ?1")
+        .bind(name)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to delete wallet group: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// `false` if the wallet already is a member.
+pub async fn add_member(
+    conn: &mut SqliteConnection,
+    group_name: &str,
+    wallet_name: &str,
+    added_by: &str,
+    now: i64,
+) -> Result<bool> {
+    let result = sqlx::query(
+        "INSERT INTO wallet_group_members (group_name, wallet_name, added_by, added_at) VALUES (?1, ?2, ?3, ?4) \
+         ON CONFLICT(group_name, wallet_name) DO NOTHING",
+    )
+    .bind(group_name)
+    .bind(wallet_name)
+    .bind(added_by)
+    .bind(now)
+    .execute(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to add wallet group member: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn remove_member(conn: &mut SqliteConnection, group_name: &str, wallet_name: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM wallet_group_members WHERE group_name = ?1 AND wallet_name = ?2")
+        .bind(group_name)
+        .bind(wallet_name)
+        .execute(conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to remove wallet group member: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Ordered by wallet name.
+pub async fn members(pool: &SqlitePool, group_name: &str) -> Result<Vec<WalletGroupMember>> {
+    let rows = sqlx::query_as::<_, WalletGroupMember>(
+        "SELECT wallet_name, added_by, added_at FROM wallet_group_members WHERE group_name = ?1 ORDER BY wallet_name",
+    )
+    .bind(group_name)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Drops `wallet_name` from every group; returns how many groups it was in.
+pub async fn delete_memberships(conn: &mut SqliteConnection, wallet_name: &str) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM wallet_group_members WHERE wallet_name = ?1")
+        .bind(wallet_name)
+        .execute(conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to drop wallet group memberships: {}", e))?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,236 @@
+//! Per wallet+network initialization state.
+//!
+//! A row in `wallet_networks` means the wallet has been registered on that
+//! network: its address is known, the local nonce counter was seeded and a
+//! deposit-scan cursor exists. When the chain could not be reached at
+//! registration time the row is written with status `needs_sync` and no
+//! nonce/cursor height, so a later initialize call can complete it.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow, SqliteConnection};
+
+/// Address, nonce and scan cursor are all in place
+pub const NETWORK_READY: &str = "ready";
+/// Registered, but the on-chain nonce / tip height still has to be fetched
+pub const NETWORK_NEEDS_SYNC: &str = "needs_sync";
+
+/// What gets written for one network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkInit {
+    pub network: String,
+    pub address: String,
+    /// On-chain transaction count; `None` when the RPC call failed
+    pub nonce: Option<u64>,
+    /// Block height deposits are scanned from; `None` when the RPC call failed
+    pub scan_from_block: Option<u64>,
+    /// Why the network is `needs_sync`
+    pub error: Option<String>,
+}
+
+impl NetworkInit {
+    pub fn status(&self) -> &'static str {
+        if self.nonce.is_some() && self.scan_from_block.is_some() {
+            NETWORK_READY
+        } else {
+            NETWORK_NEEDS_SYNC
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct WalletNetworkRecord {
+    pub wallet_name: String,
+    pub network: String,
+    pub address: String,
+    /// [`NETWORK_READY`] or [`NETWORK_NEEDS_SYNC`]
+    pub status: String,
+    pub initial_nonce: Option<i64>,
+    pub last_error: Option<String>,
+    /// Unix seconds
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct DepositScanCursor {
+    pub wallet_name: String,
+    pub network: String,
+    pub address: String,
+    /// Next block to scan; `None` until the tip height is known
+    pub next_block: Option<i64>,
+    pub updated_at: i64,
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS wallet_networks (
+            wallet_name TEXT NOT NULL,
+            network TEXT NOT NULL,
+            address TEXT NOT NULL,
+            status TEXT NOT NULL,
+            initial_nonce INTEGER,
+            last_error TEXT,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (wallet_name, network)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS deposit_scan_cursors (
+            wallet_name TEXT NOT NULL,
+            network TEXT NOT NULL,
+            address TEXT NOT NULL,
+            next_block INTEGER,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (wallet_name, network)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Writes the network row, seeds `nonces` and creates the scan cursor.
+///
+/// Re-running for a `needs_sync` network fills in what was missing; a nonce
+/// counter that has already moved past the on-chain count is never lowered,
+/// and an existing cursor height is kept.
+pub async fn upsert(
+    conn: &mut SqliteConnection,
+    wallet_name: &str,
+    init: &NetworkInit,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO wallet_networks (wallet_name, network, address, status, initial_nonce, last_error, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        ON CONFLICT(wallet_name, network) DO UPDATE SET
+            address = excluded.address,
+            status = excluded.status,
+            initial_nonce = COALESCE(excluded.initial_nonce, wallet_networks.initial_nonce),
+            last_error = excluded.last_error,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(wallet_name)
+    .bind(&init.network)
+    .bind(&init.address)
+    .bind(init.status())
+    .bind(init.nonce.map(|n| n as i64))
+    .bind(&init.error)
+    .bind(now.timestamp())
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store wallet network: {}", e))?;
+
+    if let Some(nonce) = init.nonce {
+        sqlx::query(
+            r#"
+            INSERT INTO nonces (network, address, next_nonce, updated_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(network, address) DO UPDATE SET
+                next_nonce = MAX(nonces.next_nonce, excluded.next_nonce),
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&init.network)
+        .bind(&init.address)
+        .bind(nonce as i64)
+        .bind(now.naive_utc())
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to seed nonce: {}", e))?;
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO deposit_scan_cursors (wallet_name, network, address, next_block, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        ON CONFLICT(wallet_name, network) DO UPDATE SET
+            address = excluded.address,
+            next_block = COALESCE(deposit_scan_cursors.next_block, excluded.next_block),
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(wallet_name)
+    .bind(&init.network)
+    .bind(&init.address)
+    .bind(init.scan_from_block.map(|b| b as i64))
+    .bind(now.timestamp())
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to create deposit scan cursor: {}", e))?;
+    Ok(())
+}
+
+/// Rows of a wallet whose creation is still pending are not returned.
+/// Non-custodial wallets have network rows but no `wallets` row.
+pub async fn for_wallet(pool: &SqlitePool, wallet_name: &str) -> Result<Vec<WalletNetworkRecord>> {
+    Ok(sqlx::query_as::<_, WalletNetworkRecord>(
+        "SELECT wallet_name, network, address, status, initial_nonce, last_error, updated_at \
+         FROM wallet_networks WHERE wallet_name = ?1 \
+         AND NOT EXISTS (SELECT 1 FROM wallets w WHERE w.name = ?1 AND w.creation_state = 'pending') \
+         ORDER BY network",
+    )
+    .bind(wallet_name)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Every wallet registered on `network`, by wallet name; same pending rule
+/// as [`for_wallet`].
+pub async fn on_network(pool: &SqlitePool, network: &str) -> Result<Vec<WalletNetworkRecord>> {
+    Ok(sqlx::query_as::<_, WalletNetworkRecord>(
+        "SELECT n.wallet_name, n.network, n.address, n.status, n.initial_nonce, n.last_error, n.updated_at \
+         FROM wallet_networks n WHERE n.network = ?1 \
+         AND NOT EXISTS (SELECT 1 FROM wallets w WHERE w.name = n.wallet_name AND w.creation_state = 'pending') \
+         ORDER BY n.wallet_name",
+    )
+    .bind(network)
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn scan_cursor(
+    pool: &SqlitePool,
+    wallet_name: &str,
+    network: &str,
+) -> Result<Option<DepositScanCursor>> {
+    Ok(sqlx::query_as::<_, DepositScanCursor>(
+        "SELECT wallet_name, network, address, next_block, updated_at \
+         FROM deposit_scan_cursors WHERE wallet_name = ?1 AND network = ?2",
+    )
+    .bind(wallet_name)
+    .bind(network)
+    .fetch_optional(pool)
+    .await?)
+}
+
+/// Drops the network rows and cursors of a deleted wallet (nonce counters are
+/// keyed by address and stay, so a re-imported key never reuses a nonce)
+pub async fn delete_for_wallet(conn: &mut SqliteConnection, wallet_name: &str) -> Result<()> {
+    for table in ["wallet_networks", "deposit_scan_cursors"] {
+        sqlx::query(&format!("DELETE FROM {} WHERE wallet_name = ?1", table))
+            .bind(wallet_name)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn rename(conn: &mut SqliteConnection, wallet_name: &str, new_name: &str) -> Result<()> {
+    for table in ["wallet_networks", "deposit_scan_cursors"] {
+        sqlx::query(&format!("UPDATE {} SET wallet_name = ?1 WHERE wallet_name = ?2", table))
+            .bind(new_name)
+            .bind(wallet_name)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,241 @@
+//! Free-form notes attached to a wallet: a `description` and a flat
+//! `metadata` JSON object, both stored on the `wallets` row.
+//!
+//! Metadata updates are JSON merge patches applied inside one write
+//! transaction, so two concurrent patches touching different keys both land.
+//! Search matches a substring of the name, the description or any scalar
+//! metadata value. A leading-wildcard `LIKE` cannot use a b-tree index, so
+//! the search is a scan of the wallets table; it stays cheap because the
+//! table holds one row per wallet.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+
+use super::cache_epochs;
+
+pub const MAX_DESCRIPTION_BYTES: usize = 1024;
+/// Serialized size of the merged metadata object
+pub const MAX_METADATA_BYTES: usize = 4096;
+pub const MAX_METADATA_KEYS: usize = 32;
+pub const MAX_METADATA_KEY_LEN: usize = 64;
+
+/// Description and metadata of one wallet
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WalletNotes {
+    pub description: Option<String>,
+    pub metadata: Map<String, Value>,
+}
+
+impl WalletNotes {
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none() && self.metadata.is_empty()
+    }
+}
+
+/// Outcome of [`update`]; nothing is written unless it is `Updated`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotesUpdate {
+    Updated(WalletNotes),
+    NotFound,
+    MetadataTooLarge(usize),
+    TooManyKeys(usize),
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    for (column, ddl) in [
+        ("description", "ALTER TABLE wallets ADD COLUMN description TEXT"),
+        ("metadata", "ALTER TABLE wallets ADD COLUMN metadata TEXT"),
+    ] {
+        let has_column: bool =
+            sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_table_info('wallets') WHERE name = ?")
+                .bind(column)
+                .fetch_one(pool)
+                .await?;
+        if !has_column {
+            sqlx::query(ddl)
+                .execute(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to add wallets.{}: {}", column, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `key` is a valid metadata key: 1-64 chars of `[a-z0-9_.]`.
+pub fn is_valid_metadata_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= MAX_METADATA_KEY_LEN
+        && key.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'.')
+}
+
+/// `%query%` with the `LIKE` wildcards in `query` escaped by `\`; pair with
+/// `ESCAPE '\'`.
+pub fn like_pattern(query: &str) -> String {
+    let mut pattern = String::with_capacity(query.len() + 2);
+    pattern.push('%');
+    for c in query.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+fn decode(description: Option<String>, metadata: Option<String>) -> Result<WalletNotes> {
+    let metadata = match metadata {
+        Some(raw) => serde_json::from_str(&raw).map_err(|e| anyhow::anyhow!("Corrupt wallet metadata: {}", e))?,
+        None => Map::new(),
+    };
+    Ok(WalletNotes { description, metadata })
+}
+
+fn encode_metadata(metadata: &Map<String, Value>) -> Result<Option<String>> {
+    if metadata.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::to_string(metadata)?))
+}
+
+/// Notes of `name`, `None` when the wallet does not exist
+pub async fn get(pool: &SqlitePool, name: &str) -> Result<Option<WalletNotes>> {
+    let row: Option<(Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT description, metadata FROM wallets WHERE name = ?")
+            .bind(name)
+            .fetch_optional(pool)
+            .await?;
+    row.map(|(description, metadata)| decode(description, metadata)).transpose()
+}
+
+/// Notes of each wallet in `names` that has any
+pub async fn for_names(pool: &SqlitePool, names: &[String]) -> Result<HashMap<String, WalletNotes>> {
+    if names.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<(String, Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT name, description, metadata FROM wallets \
+         WHERE name IN (SELECT value FROM json_each(?)) \
+           AND (description IS NOT NULL OR metadata IS NOT NULL)",
+    )
+    .bind(serde_json::to_string(names)?)
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter().map(|(name, description, metadata)| Ok((name, decode(description, metadata)?))).collect()
+}
+
+/// Applies `patch` to the stored metadata with JSON merge-patch semantics
+/// (a `null` value removes the key) and replaces the description when
+/// `description` is `Some` (`Some(None)` clears it). The caps apply to the
+/// merged result. Notes show up in wallet listings, so a write advances the
+/// wallets cache epoch.
+pub async fn update(
+    pool: &SqlitePool,
+    name: &str,
+    description: Option<Option<String>>,
+    patch: &Map<String, Value>,
+    now: chrono::NaiveDateTime,
+) -> Result<NotesUpdate> {
+    let mut tx = pool.begin().await?;
+    // Taking the write lock first means no other patch can interleave
+    // between the read below and the write.
+    let touched = sqlx::query("UPDATE wallets SET updated_at = ? WHERE name = ?")
+        .bind(now)
+        .bind(name)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if touched == 0 {
+        return Ok(NotesUpdate::NotFound);
+    }
+    let (stored_description, stored_metadata): (Option<String>, Option<String>) =
+        sqlx::query_as("SELECT description, metadata FROM wallets WHERE name = ?")
+            .bind(name)
+            .fetch_one(&mut *tx)
+            .await?;
+    let mut notes = decode(stored_description, stored_metadata)?;
+
+    for (key, value) in patch {
+        if value.is_null() {
+            notes.metadata.remove(key);
+        } else {
+            notes.metadata.insert(key.clone(), value.clone());
+        }
+    }
+    if notes.metadata.len() > MAX_METADATA_KEYS {
+        return Ok(NotesUpdate::TooManyKeys(notes.metadata.len()));
+    }
+    let metadata = encode_metadata(&notes.metadata)?;
+    let size = metadata.as_ref().map_or(0, String::len);
+    if size > MAX_METADATA_BYTES {
+        return Ok(NotesUpdate::MetadataTooLarge(size));
+    }
+    if let Some(description) = description {
+        notes.description = description;
+    }
+
+    sqlx::query("UPDATE wallets SET description = ?, metadata = ? WHERE name = ?")
+        .bind(&notes.description)
+        .bind(metadata)
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    cache_epochs::bump(&mut tx, cache_epochs::WALLETS, now.and_utc().timestamp()).await?;
+    tx.commit().await?;
+    Ok(NotesUpdate::Updated(notes))
+}
+
+/// Overwrites the notes of `name` (restore path); returns whether the wallet exists.
+pub async fn set(pool: &SqlitePool, name: &str, notes: &WalletNotes, now: i64) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+    let result = sqlx::query("UPDATE wallets SET description = ?, metadata = ? WHERE name = ?")
+        .bind(&notes.description)
+        .bind(encode_metadata(&notes.metadata)?)
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+    cache_epochs::bump(&mut tx, cache_epochs::WALLETS, now).await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Names of wallets whose name, description or a scalar metadata value
+/// contains `query`, compared case-insensitively (ASCII only, as SQLite's
+/// `LIKE`).
+pub async fn search(pool: &SqlitePool, query: &str) -> Result<Vec<String>> {
+    let names = sqlx::query_scalar(
+        r"SELECT name FROM wallets
+          WHERE name LIKE ?1 ESCAPE '\'
+             OR description LIKE ?1 ESCAPE '\'
+             OR EXISTS (
+                 SELECT 1 FROM json_each(wallets.metadata) m
+                 WHERE m.type IN ('text', 'integer', 'real') AND CAST(m.value AS TEXT) LIKE ?1 ESCAPE '\'
+             )
+          ORDER BY name",
+    )
+    .bind(like_pattern(query))
+    .fetch_all(pool)
+    .await?;
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern("50%_off"), r"%50\%\_off%");
+        assert_eq!(like_pattern(r"a\b"), r"%a\\b%");
+        assert!(is_valid_metadata_key("team.cost_center2"));
+        assert!(!is_valid_metadata_key("Team"));
+        assert!(!is_valid_metadata_key(""));
+        assert!(!is_valid_metadata_key(&"a".repeat(65)));
+    }
+}
@@ -0,0 +1,114 @@
+//! Keyset pagination over the `wallets` table.
+//!
+//! Pages are ordered newest first by `(created_at, id)` and the cursor is the
+//! last row's key, so fetching page N costs the same as page 1 (no OFFSET).
+//! Only metadata columns are selected; encrypted blobs stay on disk.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use base64::Engine;
+use sqlx::{sqlite::SqlitePool, types::chrono::NaiveDateTime};
+
+use super::WalletMetadata;
+
+/// Upper bound for a single page.
+pub const MAX_WALLET_PAGE_SIZE: usize = 500;
+
+const CURSOR_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+// Wallets whose creation never completed are not listed.
+pub(crate) const FIRST_PAGE_SQL: &str = "SELECT id, name, quantum_safe, created_at, updated_at FROM wallets \
+     WHERE creation_state = 'complete' \
+     ORDER BY created_at DESC, id DESC LIMIT ?1";
+
+pub(crate) const NEXT_PAGE_SQL: &str = "SELECT id, name, quantum_safe, created_at, updated_at FROM wallets \
+     WHERE creation_state = 'complete' AND (created_at, id) < (?1, ?2) \
+     ORDER BY created_at DESC, id DESC LIMIT ?3";
+
+/// Position after the last wallet returned by a page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletCursor {
+    pub created_at: NaiveDateTime,
+    pub id: String,
+}
+
+impl WalletCursor {
+    fn from_wallet(w: &WalletMetadata) -> Self {
+        Self { created_at: w.created_at, id: w.id.clone() }
+    }
+
+    /// Opaque URL-safe token handed to API clients.
+    pub fn encode(&self) -> String {
+        let raw = format!("{}|{}", self.created_at.format(CURSOR_TIME_FORMAT), self.id);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(token: &str) -> Result<Self> {
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(token.trim())
+            .map_err(|_| anyhow::anyhow!("Invalid wallet cursor"))?;
+        let raw = String::from_utf8(bytes).map_err(|_| anyhow::anyhow!("Invalid wallet cursor"))?;
+        let (ts, id) = raw.split_once('|').ok_or_else(|| anyhow::anyhow!("Invalid wallet cursor"))?;
+        let created_at = NaiveDateTime::parse_from_str(ts, CURSOR_TIME_FORMAT)
+            .map_err(|_| anyhow::anyhow!("Invalid wallet cursor"))?;
+        Ok(Self { created_at, id: id.to_string() })
+    }
+}
+
+/// One page of wallet metadata. Entries are `Arc`-shared so enrichment layers
+/// can hold on to them without cloning names.
+#[derive(Debug, Clone, Default)]
+pub struct WalletPage {
+    pub wallets: Vec<Arc<WalletMetadata>>,
+    pub next_cursor: Option<WalletCursor>,
+}
+
+pub async fn init_indexes(pool: &SqlitePool) -> Result<()> {
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_wallets_created_id ON wallets (created_at, id)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Metadata of one complete wallet.
+pub async fn by_name(pool: &SqlitePool, name: &str) -> Result<Option<WalletMetadata>> {
+    sqlx::query_as::<_, WalletMetadata>(
+        "SELECT id, name, quantum_safe, created_at, updated_at FROM wallets \
+         WHERE name = ?1 AND creation_state = 'complete'",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to look up wallet: {}", e))
+}
+
+/// Fetches up to `limit` wallets after `cursor` (newest first).
+pub async fn list_page(
+    pool: &SqlitePool,
+    cursor: Option<&WalletCursor>,
+    limit: usize,
+) -> Result<WalletPage> {
+    let limit = limit.clamp(1, MAX_WALLET_PAGE_SIZE);
+    // Fetch one extra row to learn whether another page exists.
+    let fetch = (limit + 1) as i64;
+
+    let mut rows = match cursor {
+        None => sqlx::query_as::<_, WalletMetadata>(FIRST_PAGE_SQL).bind(fetch).fetch_all(pool).await,
+        Some(c) => {
+            sqlx::query_as::<_, WalletMetadata>(NEXT_PAGE_SQL)
+                .bind(c.created_at)
+                .bind(&c.id)
+                .bind(fetch)
+                .fetch_all(pool)
+                .await
+        }
+    }
+    .map_err(|e| anyhow::anyhow!("Failed to list wallets: {}", e))?;
+
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+    let next_cursor = if has_more { rows.last().map(WalletCursor::from_wallet) } else { None };
+
+    Ok(WalletPage { wallets: rows.into_iter().map(Arc::new).collect(), next_cursor })
+}
@@ -0,0 +1,275 @@
+//! Per-wallet behavioral profiles for anomaly detection.
+//!
+//! One `wallet_profiles` row per (wallet, network) holds the rolling
+//! aggregates of [`WalletProfile`]; `wallet_profile_recipients` remembers
+//! every address the wallet has paid, which backs both the distinct
+//! recipient count and recipient novelty. Both are folded forward by
+//! [`apply_send`] when a transaction turns `confirmed`, in the same database
+//! transaction as the status change. A confirmation that is later reverted
+//! stays counted until [`rebuild`] recomputes the profiles from history.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow, Row, SqliteConnection};
+
+use crate::anomaly_detection::profile::WalletProfile;
+use crate::core::amount::{Amount, AssetTag};
+
+/// A stored decimal amount as the profile's statistical feature. The string
+/// is parsed exactly; only the returned main-unit value is approximate.
+/// Networks without a known native denomination are read with 18 decimals.
+pub(crate) fn feature_amount(network: &str, amount: &str) -> Option<f64> {
+    Amount::parse_native(network, amount)
+        .or_else(|_| Amount::parse(amount, 18, AssetTag::new(network, "")))
+        .ok()
+        .map(|a| a.to_f64_lossy())
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS wallet_profiles (
+            wallet_id TEXT NOT NULL,
+            network TEXT NOT NULL,
+            sample_count INTEGER NOT NULL,
+            mean_amount REAL NOT NULL,
+            m2_amount REAL NOT NULL,
+            max_amount REAL NOT NULL,
+            amount_buckets TEXT NOT NULL,
+            hour_histogram TEXT NOT NULL,
+            distinct_recipients INTEGER NOT NULL,
+            first_send_at INTEGER,
+            last_send_at INTEGER,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (wallet_id, network)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS wallet_profile_recipients (
+            wallet_id TEXT NOT NULL,
+            network TEXT NOT NULL,
+            recipient TEXT NOT NULL,
+            first_paid_at INTEGER NOT NULL,
+            PRIMARY KEY (wallet_id, network, recipient)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// What an admin rebuild recomputed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProfileRebuildReport {
+    /// (wallet, network) profiles written
+    pub profiles: usize,
+    /// Confirmed transactions replayed
+    pub transactions: usize,
+}
+
+#[derive(FromRow)]
+struct ProfileRow {
+    wallet_id: String,
+    network: String,
+    sample_count: i64,
+    mean_amount: f64,
+    m2_amount: f64,
+    max_amount: f64,
+    amount_buckets: String,
+    hour_histogram: String,
+    distinct_recipients: i64,
+    first_send_at: Option<i64>,
+    last_send_at: Option<i64>,
+}
+
+impl ProfileRow {
+    fn into_profile(self) -> Result<WalletProfile> {
+        let corrupt = |field: &str| anyhow::anyhow!("Corrupt {} on wallet profile {}/{}", field, self.wallet_id, self.network);
+        let amount_buckets = serde_json::from_str(&self.amount_buckets).map_err(|_| corrupt("amount_buckets"))?;
+        let hour_histogram = serde_json::from_str(&self.hour_histogram).map_err(|_| corrupt("hour_histogram"))?;
+        Ok(WalletProfile {
+            sample_count: self.sample_count as u64,
+            mean_amount: self.mean_amount,
+            m2_amount: self.m2_amount,
+            max_amount: self.max_amount,
+            amount_buckets,
+            hour_histogram,
+            distinct_recipients: self.distinct_recipients as u64,
+            first_send_at: self.first_send_at,
+            last_send_at: self.last_send_at,
+            wallet_id: self.wallet_id,
+            network: self.network,
+        })
+    }
+}
+
+pub async fn get(conn: &mut SqliteConnection, wallet_id: &str, network: &str) -> Result<Option<WalletProfile>> {
+    sqlx::query_as::<_, ProfileRow>(
+        "SELECT wallet_id, network, sample_count, mean_amount, m2_amount, max_amount, amount_buckets, \
+         hour_histogram, distinct_recipients, first_send_at, last_send_at \
+         FROM wallet_profiles WHERE wallet_id = ?1 AND network = ?2",
+    )
+    .bind(wallet_id)
+    .bind(network)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load wallet profile: {}", e))?
+    .map(ProfileRow::into_profile)
+    .transpose()
+}
+
+pub async fn is_known_recipient(pool: &SqlitePool, wallet_id: &str, network: &str, recipient: &str) -> Result<bool> {
+    let row = sqlx::query(
+        "SELECT 1 FROM wallet_profile_recipients WHERE wallet_id = ?1 AND network = ?2 AND recipient = ?3",
+    )
+    .bind(wallet_id)
+    .bind(network)
+    .bind(recipient.to_lowercase())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to look up profile recipient: {}", e))?;
+    Ok(row.is_some())
+}
+
+async fn upsert(conn: &mut SqliteConnection, profile: &WalletProfile, now: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO wallet_profiles (
+            wallet_id, network, sample_count, mean_amount, m2_amount, max_amount, amount_buckets,
+            hour_histogram, distinct_recipients, first_send_at, last_send_at, updated_at
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+        ON CONFLICT(wallet_id, network) DO UPDATE SET
+            sample_count = excluded.sample_count,
+            mean_amount = excluded.mean_amount,
+            m2_amount = excluded.m2_amount,
+            max_amount = excluded.max_amount,
+            amount_buckets = excluded.amount_buckets,
+            hour_histogram = excluded.hour_histogram,
+            distinct_recipients = excluded.distinct_recipients,
+            first_send_at = excluded.first_send_at,
+            last_send_at = excluded.last_send_at,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&profile.wallet_id)
+    .bind(&profile.network)
+    .bind(profile.sample_count as i64)
+    .bind(profile.mean_amount)
+    .bind(profile.m2_amount)
+    .bind(profile.max_amount)
+    .bind(serde_json::to_string(&profile.amount_buckets)?)
+    .bind(serde_json::to_string(&profile.hour_histogram)?)
+    .bind(profile.distinct_recipients as i64)
+    .bind(profile.first_send_at)
+    .bind(profile.last_send_at)
+    .bind(now)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store wallet profile: {}", e))?;
+    Ok(())
+}
+
+/// Folds one confirmed send into the wallet's profile: one recipient insert,
+/// one profile read and one upsert, independent of history length.
+pub async fn apply_send(
+    conn: &mut SqliteConnection,
+    wallet_id: &str,
+    network: &str,
+    recipient: &str,
+    amount: f64,
+    sent_at: i64,
+    now: i64,
+) -> Result<()> {
+    let inserted = sqlx::query(
+        "INSERT OR IGNORE INTO wallet_profile_recipients (wallet_id, network, recipient, first_paid_at) \
+         VALUES (?1, ?2, ?3, ?4)",
+    )
+    .bind(wallet_id)
+    .bind(network)
+    .bind(recipient.to_lowercase())
+    .bind(sent_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record profile recipient: {}", e))?;
+
+    let mut profile = get(conn, wallet_id, network).await?.unwrap_or_else(|| WalletProfile::new(wallet_id, network));
+    profile.record_send(amount, sent_at, inserted.rows_affected() == 1);
+    upsert(conn, &profile, now).await
+}
+
+/// Recomputes profiles from scratch by replaying confirmed transactions in
+/// creation order; every wallet when `wallet_id` is `None`. Transactions
+/// whose amount does not parse are skipped, as in the incremental path.
+pub async fn rebuild(pool: &SqlitePool, wallet_id: Option<&str>, now: i64) -> Result<ProfileRebuildReport> {
+    let mut tx = pool.begin().await?;
+    for table in ["wallet_profiles", "wallet_profile_recipients"] {
+        sqlx::query(&format!("DELETE FROM {} WHERE ?1 IS NULL OR wallet_id = ?1", table))
+            .bind(wallet_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to clear wallet profiles: {}", e))?;
+    }
+
+    let rows = sqlx::query(
+        "SELECT w.name AS wallet_name, t.network, t.to_address, t.amount, t.created_at \
+         FROM transactions t JOIN wallets w ON w.id = t.wallet_id \
+         WHERE t.status = 'confirmed' AND (?1 IS NULL OR w.name = ?1) ORDER BY t.created_at, t.id",
+    )
+    .bind(wallet_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load confirmed transactions: {}", e))?;
+
+    let mut profiles: HashMap<(String, String), WalletProfile> = HashMap::new();
+    let mut recipients: HashMap<(String, String), HashMap<String, i64>> = HashMap::new();
+    let mut replayed = 0;
+    for row in rows {
+        let wallet: String = row.get("wallet_name");
+        let network: String = row.get("network");
+        let Some(amount) = feature_amount(&network, &row.get::<String, _>("amount")) else {
+            continue;
+        };
+        let sent_at = row.get::<chrono::DateTime<chrono::Utc>, _>("created_at").timestamp();
+        let recipient = row.get::<String, _>("to_address").to_lowercase();
+
+        let key = (wallet.clone(), network.clone());
+        let seen = recipients.entry(key.clone()).or_default();
+        let new_recipient = !seen.contains_key(&recipient);
+        seen.entry(recipient).or_insert(sent_at);
+        profiles
+            .entry(key)
+            .or_insert_with(|| WalletProfile::new(&wallet, &network))
+            .record_send(amount, sent_at, new_recipient);
+        replayed += 1;
+    }
+
+    for profile in profiles.values() {
+        upsert(&mut tx, profile, now).await?;
+    }
+    for ((wallet, network), seen) in &recipients {
+        for (recipient, first_paid_at) in seen {
+            sqlx::query(
+                "INSERT INTO wallet_profile_recipients (wallet_id, network, recipient, first_paid_at) \
+                 VALUES (?1, ?2, ?3, ?4)",
+            )
+            .bind(wallet)
+            .bind(network)
+            .bind(recipient)
+            .bind(first_paid_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to record profile recipient: {}", e))?;
+        }
+    }
+    tx.commit().await.map_err(|e| anyhow::anyhow!("Failed to rebuild wallet profiles: {}", e))?;
+    Ok(ProfileRebuildReport { profiles: profiles.len(), transactions: replayed })
+}
@@ -0,0 +1,327 @@
+//! Wallet-scoped API tokens for third-party integrations.
+//!
+//! A token is bound to one user wallet (`wallet_id` is the `user_wallets` row
+//! id in users.db; owner and name are copied so requests can be matched
+//! without touching that database) and carries a capability bitset plus an
+//! optional per-transaction amount cap. Only the SHA-256 of the token is
+//! stored; the plaintext is shown once at creation.
+//!
+//! Each token is an API key, and its credential lives in `api_keys` (one row
+//! per token, keyed by the token id) with the key's `auth_mode`. `bearer`
+//! tokens are sent as is in the `Authorization` header. `hmac` tokens are
+//! signing keys: the client signs each request with the secret and sends only
+//! the token id, so the server keeps the secret, sealed under the KEK (see
+//! [`crate::security::signing_secret`]), and it is never accepted as a bearer
+//! token.
+
+use anyhow::Result;
+use serde::{Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use sqlx::{sqlite::SqlitePool, FromRow};
+
+use crate::security::signing_secret::SealedSigningSecret;
+
+/// [`WalletTokenRecord::auth_mode`] of tokens sent in the `Authorization` header
+pub const AUTH_MODE_BEARER: &str = "bearer";
+/// [`WalletTokenRecord::auth_mode`] of per-request signing keys
+pub const AUTH_MODE_HMAC: &str = "hmac";
+
+/// Operations a wallet token can be granted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletCapability {
+    ReadHistory,
+    ReadBalance,
+    Send,
+}
+
+impl WalletCapability {
+    pub const ALL: [WalletCapability; 3] =
+        [WalletCapability::ReadHistory, WalletCapability::ReadBalance, WalletCapability::Send];
+
+    pub fn bit(self) -> i64 {
+        match self {
+            WalletCapability::ReadHistory => 1,
+            WalletCapability::ReadBalance => 1 << 1,
+            WalletCapability::Send => 1 << 2,
+        }
+    }
+
+    /// Name used in the API
+    pub fn name(self) -> &'static str {
+        match self {
+            WalletCapability::ReadHistory => "read_history",
+            WalletCapability::ReadBalance => "read_balance",
+            WalletCapability::Send => "send",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.name() == name)
+    }
+}
+
+fn serialize_capabilities<S: Serializer>(bits: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        WalletCapability::ALL.into_iter().filter(|c| bits & c.bit() != 0).map(WalletCapability::name),
+    )
+}
+
+/// A token row with its key's auth mode; neither the hash nor the signing
+/// secret is loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct WalletTokenRecord {
+    pub id: String,
+    pub wallet_id: i64,
+    pub wallet_name: String,
+    pub owner_user_id: String,
+    /// Bitset of [`WalletCapability::bit`]; serialized as names
+    #[serde(serialize_with = "serialize_capabilities")]
+    pub capabilities: i64,
+    /// Largest amount a single send may move (decimal, whole units)
+    pub amount_cap: Option<String>,
+    /// Unix seconds
+    pub expires_at: i64,
+    /// User id of the owner, or `admin`
+    pub created_by: String,
+    pub created_at: i64,
+    pub revoked_at: Option<i64>,
+    /// [`AUTH_MODE_BEARER`] or [`AUTH_MODE_HMAC`]
+    pub auth_mode: String,
+}
+
+impl WalletTokenRecord {
+    pub fn allows(&self, capability: WalletCapability) -> bool {
+        self.capabilities & capability.bit() != 0
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// Fields of a new `wallet_tokens` row.
+#[derive(Debug, Clone)]
+pub struct NewWalletToken<'a> {
+    pub id: &'a str,
+    /// From [`hash_token`]
+    pub token_hash: &'a str,
+    pub wallet_id: i64,
+    pub wallet_name: &'a str,
+    pub owner_user_id: &'a str,
+    pub capabilities: i64,
+    pub amount_cap: Option<&'a str>,
+    pub expires_at: i64,
+    pub created_by: &'a str,
+    /// [`AUTH_MODE_BEARER`] or [`AUTH_MODE_HMAC`]; for a signing key
+    /// `token_hash` is the hash of the secret and never matches a bearer lookup
+    pub auth_mode: &'a str,
+    /// Sealed secret of an [`AUTH_MODE_HMAC`] key
+    pub signing_secret: Option<&'a SealedSigningSecret>,
+}
+
+/// Hex SHA-256 of the plaintext token
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS wallet_tokens (
+            id TEXT PRIMARY KEY,
+            token_hash TEXT NOT NULL UNIQUE,
+            wallet_id INTEGER NOT NULL,
+            wallet_name TEXT NOT NULL,
+            owner_user_id TEXT NOT NULL,
+            capabilities INTEGER NOT NULL,
+            amount_cap TEXT,
+            expires_at INTEGER NOT NULL,
+            created_by TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            revoked_at INTEGER
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS api_keys (
+            key_id TEXT PRIMARY KEY,
+            auth_mode TEXT NOT NULL,
+            secret_salt BLOB,
+            secret_nonce BLOB,
+            secret_ciphertext BLOB
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_wallet_tokens_wallet ON wallet_tokens (owner_user_id, wallet_name)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Inserts the token and its `api_keys` row together.
+pub async fn insert(pool: &SqlitePool, token: &NewWalletToken<'_>, now: i64) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        r#"
+        INSERT INTO wallet_tokens (
+            id, token_hash, wallet_id, wallet_name, owner_user_id, capabilities,
+            amount_cap, expires_at, created_by, created_at
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        "#,
+    )
+    .bind(token.id)
+    .bind(token.token_hash)
+    .bind(token.wallet_id)
+    .bind(token.wallet_name)
+    .bind(token.owner_user_id)
+    .bind(token.capabilities)
+    .bind(token.amount_cap)
+    .bind(token.expires_at)
+    .bind(token.created_by)
+    .bind(now)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store wallet token: {}", e))?;
+    insert_api_key(&mut tx, token.id, token.auth_mode, token.signing_secret).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// The `api_keys` row of token `key_id`
+pub async fn insert_api_key(
+    conn: &mut sqlx::SqliteConnection,
+    key_id: &str,
+    auth_mode: &str,
+    secret: Option<&SealedSigningSecret>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO api_keys (key_id, auth_mode, secret_salt, secret_nonce, secret_ciphertext)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        "#,
+    )
+    .bind(key_id)
+    .bind(auth_mode)
+    .bind(secret.map(|s| s.salt.as_slice()))
+    .bind(secret.map(|s| s.nonce.as_slice()))
+    .bind(secret.map(|s| s.ciphertext.as_slice()))
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to store API key: {}", e))?;
+    Ok(())
+}
+
+const RECORD_COLUMNS: &str = "t.id, t.wallet_id, t.wallet_name, t.owner_user_id, t.capabilities, t.amount_cap, \
+                              t.expires_at, t.created_by, t.created_at, t.revoked_at, k.auth_mode";
+
+/// Tokens joined with their `api_keys` row
+const RECORD_FROM: &str = "wallet_tokens t JOIN api_keys k ON k.key_id = t.id";
+
+/// Unrevoked bearer token with this hash; expired tokens are returned so the
+/// caller can tell them apart from unknown ones.
+pub async fn find_active(pool: &SqlitePool, token_hash: &str) -> Result<Option<WalletTokenRecord>> {
+    sqlx::query_as::<_, WalletTokenRecord>(&format!(
+        "SELECT {} FROM {} WHERE t.token_hash = ?1 AND t.revoked_at IS NULL AND k.auth_mode = ?2",
+        RECORD_COLUMNS, RECORD_FROM
+    ))
+    .bind(token_hash)
+    .bind(AUTH_MODE_BEARER)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load wallet token: {}", e))
+}
+
+/// Unrevoked signing key by id together with its sealed secret; may be expired.
+pub async fn find_signing_key(
+    pool: &SqlitePool,
+    id: &str,
+) -> Result<Option<(WalletTokenRecord, SealedSigningSecret)>> {
+    #[derive(FromRow)]
+    struct Row {
+        #[sqlx(flatten)]
+        record: WalletTokenRecord,
+        secret_salt: Vec<u8>,
+        secret_nonce: Vec<u8>,
+        secret_ciphertext: Vec<u8>,
+    }
+    let row = sqlx::query_as::<_, Row>(&format!(
+        "SELECT {}, k.secret_salt, k.secret_nonce, k.secret_ciphertext FROM {} \
+         WHERE t.id = ?1 AND t.revoked_at IS NULL AND k.auth_mode = ?2 AND k.secret_ciphertext IS NOT NULL",
+        RECORD_COLUMNS, RECORD_FROM
+    ))
+    .bind(id)
+    .bind(AUTH_MODE_HMAC)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load signing key: {}", e))?;
+    Ok(row.map(|row| {
+        let secret =
+            SealedSigningSecret { salt: row.secret_salt, nonce: row.secret_nonce, ciphertext: row.secret_ciphertext };
+        (row.record, secret)
+    }))
+}
+
+/// Unrevoked token of either mode by id; may be expired.
+pub async fn find_active_by_id(pool: &SqlitePool, id: &str) -> Result<Option<WalletTokenRecord>> {
+    sqlx::query_as::<_, WalletTokenRecord>(&format!(
+        "SELECT {} FROM {} WHERE t.id = ?1 AND t.revoked_at IS NULL",
+        RECORD_COLUMNS, RECORD_FROM
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load wallet token: {}", e))
+}
+
+/// Tokens of one wallet, newest first, revoked ones included.
+pub async fn list_for_wallet(
+    pool: &SqlitePool,
+    owner_user_id: &str,
+    wallet_name: &str,
+) -> Result<Vec<WalletTokenRecord>> {
+    sqlx::query_as::<_, WalletTokenRecord>(&format!(
+        "SELECT {} FROM {} WHERE t.owner_user_id = ?1 AND t.wallet_name = ?2 \
+         ORDER BY t.created_at DESC, t.rowid DESC",
+        RECORD_COLUMNS, RECORD_FROM
+    ))
+    .bind(owner_user_id)
+    .bind(wallet_name)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load wallet tokens: {}", e))
+}
+
+/// Returns `false` if the token does not belong to the wallet or is already revoked.
+pub async fn revoke(
+    pool: &SqlitePool,
+    owner_user_id: &str,
+    wallet_name: &str,
+    id: &str,
+    now: i64,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE wallet_tokens SET revoked_at = ?1
+        WHERE id = ?2 AND owner_user_id = ?3 AND wallet_name = ?4 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(now)
+    .bind(id)
+    .bind(owner_user_id)
+    .bind(wallet_name)
+    .execute(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to revoke wallet token: {}", e))?;
+    Ok(result.rows_affected() == 1)
+}
@@ -0,0 +1,562 @@
+//! Time-locked transactions
+//!
+//! A time lock is a transaction signed when it is created and broadcast only
+//! after its unlock conditions hold: a wall-clock time, a block height, or
+//! both. Three things keep it valid and out of reach until then:
+//!
+//! - **Nonce.** It is signed with a nonce from the address's future lane
+//!   ([`FUTURE_LANE`]), a range `lane_offset` above the pending nonce at
+//!   creation. Ordinary sends keep the nonces below the lane and are refused
+//!   with `NONCE_LANE_RESERVED` once they reach it, so they cannot invalidate
+//!   the lock. At release the account must have reached the lock's nonce
+//!   exactly: below it the release waits (`TIMELOCK_NONCE_GAP`), above it
+//!   the nonce went to another transaction and the lock is invalidated.
+//! - **Envelope.** The signed bytes are sealed with AES-256-GCM under a key
+//!   derived (HKDF-SHA256) from a random data key and the lock's id, nonce
+//!   and conditions. The data key is split 2-of-2 with [`crate::shamir`]:
+//!   the server keeps one share, the other is returned once to be handed to
+//!   an external custodian, who gives it back only after the unlock date.
+//!   Neither share alone says anything about the key.
+//! - **Re-validation.** Before broadcasting, the decrypted transaction is
+//!   checked again: chain id, sender, recipient, value and nonce as stored,
+//!   gas limit within `max_gas_limit`, and a gas price not below what the
+//!   network asks for now. A lock priced under the current floor stays
+//!   locked and can be released once fees come down.
+//!
+//! Locks not released by `expires_at` expire; they are swept whenever locks
+//! are read or created. When the last lock holding a nonce of a lane leaves
+//! `locked`, the lane closes and its range goes back to ordinary sends.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionRequest, U256};
+use ethers::utils::rlp::Rlp;
+use hkdf::Hkdf;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use crate::blockchain::gas_oracle::GasOracle;
+use crate::core::config::{BlockchainConfig, TimelockConfig, TxEncoding};
+use crate::core::errors::WalletError;
+use crate::core::rlp::tx::sign_transaction;
+use crate::intents::{IntentError, SigningIntentLog};
+use crate::security::SecretVec;
+use crate::shamir::{combine_shares, split_secret};
+use crate::storage::{
+    LaneReservation, NewTimelock, TimelockRecord, WalletStorage, FUTURE_LANE, TIMELOCK_EXPIRED, TIMELOCK_INVALIDATED,
+    TIMELOCK_LOCKED, TIMELOCK_RELEASED,
+};
+
+/// HKDF info prefix of the sealing key
+const ENVELOPE_INFO: &[u8] = b"ironcore/timelock/v1";
+const GCM_NONCE_LEN: usize = 12;
+
+/// When a lock may be released; every condition set must hold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnlockConditions {
+    /// Unix seconds
+    pub unlock_at: Option<i64>,
+    pub unlock_block: Option<u64>,
+}
+
+/// What to lock
+#[derive(Debug, Clone)]
+pub struct TimelockRequest<'a> {
+    pub network: &'a str,
+    pub to: Address,
+    pub value: U256,
+    pub data: Option<Bytes>,
+    pub conditions: UnlockConditions,
+    /// Unix seconds; defaults to the unlock time plus the release window
+    pub expires_at: Option<i64>,
+}
+
+/// A stored lock and the custodian's share of its key, shown only here
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedTimelock {
+    pub timelock: TimelockRecord,
+    /// Hex; the lock cannot be released without it
+    pub custodian_share: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TimelockError {
+    #[error("Invalid time lock: {0}")]
+    InvalidRequest(String),
+    #[error("Time lock {0} not found")]
+    NotFound(String),
+    #[error("Time lock {id} is not unlocked yet: {waiting_for}")]
+    NotYetUnlocked { id: String, waiting_for: String },
+    #[error("Time lock {id} expired at {expires_at} without being released")]
+    Expired { id: String, expires_at: i64 },
+    #[error("Time lock {id} is {status}")]
+    NotLocked { id: String, status: String },
+    #[error("Custodian share does not open time lock {0}")]
+    InvalidShare(String),
+    #[error("The time-lock nonce lane [{start}, {end}) of {address} on {network} is full")]
+    LaneExhausted { network: String, address: String, start: u64, end: u64 },
+    #[error(
+        "Lane nonce {nonce} of {address} on {network} is already used (account at nonce {account_nonce}); \
+         release or expire its pending time locks first"
+    )]
+    LaneOvertaken { network: String, address: String, nonce: u64, account_nonce: u64 },
+    #[error(
+        "Time lock {id} has nonce {nonce} but the account is at nonce {account_nonce}; \
+         the transactions before it must be sent first"
+    )]
+    NonceGap { id: String, nonce: u64, account_nonce: u64 },
+    #[error("Nonce {nonce} of time lock {id} was used by another transaction; the lock is invalidated")]
+    NonceConsumed { id: String, nonce: u64 },
+    #[error("Time-locked transaction {id} no longer passes validation: {reason}")]
+    Stale { id: String, reason: String },
+    #[error("Time-locked transaction {id} pays {gas_price} wei per gas, below the current {floor}")]
+    GasBelowFloor { id: String, gas_price: U256, floor: U256 },
+    #[error(transparent)]
+    Intent(#[from] IntentError),
+    #[error(transparent)]
+    Chain(#[from] WalletError),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+impl TimelockError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            TimelockError::InvalidRequest(_) => "INVALID_TIMELOCK",
+            TimelockError::NotFound(_) => "TIMELOCK_NOT_FOUND",
+            TimelockError::NotYetUnlocked { .. } => "TIMELOCK_NOT_YET_UNLOCKED",
+            TimelockError::Expired { .. } => "TIMELOCK_EXPIRED",
+            TimelockError::NotLocked { .. } => "TIMELOCK_NOT_LOCKED",
+            TimelockError::InvalidShare(_) => "INVALID_CUSTODIAN_SHARE",
+            TimelockError::LaneExhausted { .. } => "NONCE_LANE_EXHAUSTED",
+            TimelockError::LaneOvertaken { .. } => "NONCE_LANE_OVERTAKEN",
+            TimelockError::NonceGap { .. } => "TIMELOCK_NONCE_GAP",
+            TimelockError::NonceConsumed { .. } => "TIMELOCK_NONCE_CONSUMED",
+            TimelockError::Stale { .. } => "TIMELOCK_TRANSACTION_STALE",
+            TimelockError::GasBelowFloor { .. } => "TIMELOCK_GAS_BELOW_FLOOR",
+            TimelockError::Intent(e) => e.code(),
+            TimelockError::Chain(_) => "NETWORK_ERROR",
+            TimelockError::Storage(_) => "DB_ERROR",
+        }
+    }
+}
+
+fn hex_address(address: Address) -> String {
+    format!("{:?}", address)
+}
+
+/// Gas price of a legacy transaction, max fee per gas of an EIP-1559 one
+fn price_per_gas(tx: &TypedTransaction) -> Option<U256> {
+    match tx {
+        TypedTransaction::Eip1559(tx) => tx.max_fee_per_gas,
+        other => other.gas_price(),
+    }
+}
+
+/// HKDF info of the sealing key (the lock id is the salt): decrypting with
+/// a row whose nonce or conditions were edited fails.
+fn binding(network: &str, nonce: u64, conditions: &UnlockConditions) -> Vec<u8> {
+    let mut info = ENVELOPE_INFO.to_vec();
+    let fields = format!(
+        "|{}|{}|{}|{}",
+        network,
+        nonce,
+        conditions.unlock_at.map(|t| t.to_string()).unwrap_or_default(),
+        conditions.unlock_block.map(|b| b.to_string()).unwrap_or_default()
+    );
+    info.extend_from_slice(fields.as_bytes());
+    info
+}
+
+fn sealing_key(data_key: &[u8], id: &str, info: &[u8]) -> Result<Zeroizing<[u8; 32]>, WalletError> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(id.as_bytes()), data_key)
+        .expand(info, key.as_mut())
+        .map_err(|e| WalletError::CryptoError(format!("Failed to derive time-lock key: {}", e)))?;
+    Ok(key)
+}
+
+/// `nonce || ciphertext` of `raw`, the lock id authenticated alongside
+fn seal(key: &[u8; 32], id: &str, raw: &[u8]) -> Result<Vec<u8>, WalletError> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| WalletError::CryptoError("invalid key".into()))?;
+    let mut nonce = [0u8; GCM_NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+    let sealed = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: raw, aad: id.as_bytes() })
+        .map_err(|_| WalletError::CryptoError("Failed to seal time-locked transaction".into()))?;
+    Ok([nonce.as_slice(), &sealed].concat())
+}
+
+fn unseal(key: &[u8; 32], id: &str, sealed: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() <= GCM_NONCE_LEN {
+        return None;
+    }
+    let cipher = Aes256Gcm::new_from_slice(key).ok()?;
+    let (nonce, ciphertext) = sealed.split_at(GCM_NONCE_LEN);
+    #[allow(deprecated)] // GenericArray::from_slice is deprecated in generic-array 0.14.x
+    let nonce = Nonce::from_slice(nonce);
+    cipher.decrypt(nonce, Payload { msg: ciphertext, aad: id.as_bytes() }).ok()
+}
+
+pub struct Timelocks {
+    storage: Arc<WalletStorage>,
+    config: TimelockConfig,
+    /// network → configured chain id
+    chain_ids: HashMap<String, u64>,
+    tx_encoding: TxEncoding,
+}
+
+impl Timelocks {
+    pub fn new(config: TimelockConfig, blockchain: &BlockchainConfig, storage: Arc<WalletStorage>) -> Self {
+        let chain_ids = blockchain.networks.iter().map(|(name, network)| (name.clone(), network.chain_id)).collect();
+        Self { storage, config, chain_ids, tx_encoding: TxEncoding::default() }
+    }
+
+    /// Must match the encoding of the intent log that broadcasts the locks.
+    pub fn with_tx_encoding(mut self, encoding: TxEncoding) -> Self {
+        self.tx_encoding = encoding;
+        self
+    }
+
+    fn now(&self) -> i64 {
+        self.storage.clock().now().timestamp()
+    }
+
+    fn chain_id(&self, network: &str) -> Result<u64, TimelockError> {
+        self.chain_ids
+            .get(network)
+            .copied()
+            .ok_or_else(|| TimelockError::InvalidRequest(format!("unknown network {}", network)))
+    }
+
+    /// Expires the locks past `expires_at`, closing lanes nothing holds any more.
+    pub async fn expire_overdue(&self) -> Result<usize, TimelockError> {
+        let expired = self.storage.expire_overdue_timelocks().await?;
+        for lock in &expired {
+            warn!(
+                "time lock {} of {} expired unreleased; nonce {} of {} on {} was never broadcast",
+                lock.id, lock.wallet_name, lock.nonce, lock.from_address, lock.network
+            );
+        }
+        Ok(expired.len())
+    }
+
+    pub async fn list(&self, wallet_name: &str) -> Result<Vec<TimelockRecord>, TimelockError> {
+        self.expire_overdue().await?;
+        Ok(self.storage.wallet_timelocks(wallet_name).await?)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<TimelockRecord, TimelockError> {
+        self.expire_overdue().await?;
+        self.storage.timelock(id).await?.ok_or_else(|| TimelockError::NotFound(id.to_string()))
+    }
+
+    /// Resolved expiry of `request`; checks the conditions are in the future
+    /// and within `max_lock_secs`.
+    async fn validate(
+        &self,
+        intents: &SigningIntentLog,
+        request: &TimelockRequest<'_>,
+        now: i64,
+    ) -> Result<i64, TimelockError> {
+        let invalid = |reason: String| Err(TimelockError::InvalidRequest(reason));
+        let UnlockConditions { unlock_at, unlock_block } = request.conditions;
+        let latest = now + self.config.max_lock_secs as i64;
+        match unlock_at {
+            None if unlock_block.is_none() => return invalid("unlock_at or unlock_block is required".to_string()),
+            Some(at) if at <= now => return invalid(format!("unlock_at {} is not in the future", at)),
+            Some(at) if at > latest => {
+                return invalid(format!("unlock_at {} is more than {}s away", at, self.config.max_lock_secs))
+            }
+            _ => {}
+        }
+        if let Some(block) = unlock_block {
+            let head = intents.chain().block_number(request.network).await?;
+            if block <= head {
+                return invalid(format!("unlock_block {} is not above the current block {}", block, head));
+            }
+        }
+        // a block-only lock has no date to count the release window from
+        let expires_at = request.expires_at.unwrap_or_else(|| match unlock_at {
+            Some(at) => at + self.config.default_release_window_secs as i64,
+            None => latest,
+        });
+        if expires_at <= unlock_at.unwrap_or(now) {
+            return invalid(format!("expires_at {} is not after the unlock time", expires_at));
+        }
+        Ok(expires_at)
+    }
+
+    /// Signs `request` with a future-lane nonce and stores it sealed.
+    pub async fn create(
+        &self,
+        intents: &SigningIntentLog,
+        wallet_name: &str,
+        signer: &LocalWallet,
+        request: &TimelockRequest<'_>,
+    ) -> Result<CreatedTimelock, TimelockError> {
+        let network = request.network;
+        let chain_id = self.chain_id(network)?;
+        self.expire_overdue().await?;
+        let now = self.now();
+        let expires_at = self.validate(intents, request, now).await?;
+
+        let mut tx: TypedTransaction =
+            TransactionRequest::new().from(signer.address()).to(request.to).value(request.value).into();
+        if let Some(data) = &request.data {
+            tx.set_data(data.clone());
+        }
+        intents.chain().prepare(network, &mut tx).await?;
+        if tx.chain_id().map(|id| id.as_u64()) != Some(chain_id) {
+            return Err(WalletError::NetworkError(format!("{} node is not on chain {}", network, chain_id)).into());
+        }
+        let gas_limit = tx.gas().copied().unwrap_or_default();
+        if gas_limit > U256::from(self.config.max_gas_limit) {
+            return Err(TimelockError::InvalidRequest(format!(
+                "gas limit {} exceeds the time-lock maximum {}",
+                gas_limit, self.config.max_gas_limit
+            )));
+        }
+        let gas_price = price_per_gas(&tx).unwrap_or_default();
+        let account_nonce = tx.nonce().map(|n| n.low_u64()).unwrap_or_default();
+
+        let from = hex_address(signer.address());
+        let start = account_nonce.saturating_add(self.config.lane_offset);
+        let nonce =
+            match self.storage.reserve_lane_nonce(network, &from, FUTURE_LANE, start, self.config.lane_size).await? {
+                LaneReservation::Reserved(nonce) => nonce,
+                LaneReservation::Exhausted(lane) => {
+                    return Err(TimelockError::LaneExhausted {
+                        network: network.to_string(),
+                        address: from,
+                        start: lane.start_nonce as u64,
+                        end: lane.end_nonce as u64,
+                    })
+                }
+            };
+        match self.seal_and_store(wallet_name, signer, request, tx, nonce, account_nonce, expires_at).await {
+            Ok(timelock) => {
+                info!(
+                    "time lock {} of {}: nonce {} on {}, gas price {}, expires at {}",
+                    timelock.timelock.id, wallet_name, nonce, network, gas_price, expires_at
+                );
+                Ok(timelock)
+            }
+            Err(e) => {
+                // the reserved nonce is lost; a lane holding no lock must not keep blocking sends
+                if let Err(ce) = self.storage.close_idle_nonce_lane(network, &from).await {
+                    warn!("time-lock lane of {} on {} left open: {}", from, network, ce);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn seal_and_store(
+        &self,
+        wallet_name: &str,
+        signer: &LocalWallet,
+        request: &TimelockRequest<'_>,
+        mut tx: TypedTransaction,
+        nonce: u64,
+        account_nonce: u64,
+        expires_at: i64,
+    ) -> Result<CreatedTimelock, TimelockError> {
+        let network = request.network;
+        let from = hex_address(signer.address());
+        if nonce < account_nonce {
+            return Err(TimelockError::LaneOvertaken {
+                network: network.to_string(),
+                address: from,
+                nonce,
+                account_nonce,
+            });
+        }
+        tx.set_nonce(nonce);
+        let raw = sign_transaction(self.tx_encoding, signer, &tx)?;
+
+        let id = self.storage.id_generator().new_id();
+        let mut data_key = Zeroizing::new([0u8; 32]);
+        rand::rngs::OsRng.fill_bytes(data_key.as_mut());
+        let shares = split_secret(data_key.as_slice(), 2, 2)
+            .map_err(|e| WalletError::CryptoError(format!("Failed to split time-lock key: {}", e)))?;
+        let key = sealing_key(data_key.as_slice(), &id, &binding(network, nonce, &request.conditions))?;
+        let sealed = seal(&key, &id, &raw)?;
+
+        let lock = NewTimelock {
+            id: &id,
+            wallet_name,
+            network,
+            from_address: &from,
+            to_address: &hex_address(request.to),
+            value: &request.value.to_string(),
+            nonce: nonce as i64,
+            chain_id: tx.chain_id().map(|id| id.as_u64()).unwrap_or_default() as i64,
+            gas_limit: tx.gas().map(|g| g.low_u64()).unwrap_or_default() as i64,
+            gas_price: &price_per_gas(&tx).unwrap_or_default().to_string(),
+            unlock_at: request.conditions.unlock_at,
+            unlock_block: request.conditions.unlock_block.map(|b| b as i64),
+            expires_at,
+            sealed_tx: &hex::encode(sealed),
+            server_share: &hex::encode(shares[0].as_slice()),
+        };
+        self.storage.create_timelock(&lock).await?;
+        let timelock = self.storage.timelock(&id).await?.ok_or_else(|| TimelockError::NotFound(id.clone()))?;
+        Ok(CreatedTimelock { timelock, custodian_share: hex::encode(shares[1].as_slice()) })
+    }
+
+    /// What `lock` still waits for; `None` once every condition holds.
+    async fn waiting_for(
+        &self,
+        intents: &SigningIntentLog,
+        lock: &TimelockRecord,
+    ) -> Result<Option<String>, TimelockError> {
+        let now = self.now();
+        if let Some(at) = lock.unlock_at.filter(|at| *at > now) {
+            return Ok(Some(format!("unlocks at {} ({}s from now)", at, at - now)));
+        }
+        if let Some(block) = lock.unlock_block {
+            let head = intents.chain().block_number(&lock.network).await?;
+            if (head as i64) < block {
+                return Ok(Some(format!("unlocks at block {} (current block {})", block, head)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Opens the lock with the custodian's share, re-validates and broadcasts it.
+    pub async fn release(
+        &self,
+        intents: &SigningIntentLog,
+        oracle: &dyn GasOracle,
+        id: &str,
+        custodian_share: &str,
+    ) -> Result<TimelockRecord, TimelockError> {
+        let lock = self.get(id).await?;
+        match lock.status.as_str() {
+            TIMELOCK_LOCKED => {}
+            TIMELOCK_EXPIRED => return Err(TimelockError::Expired { id: lock.id, expires_at: lock.expires_at }),
+            _ => return Err(TimelockError::NotLocked { id: lock.id, status: lock.status }),
+        }
+        if let Some(waiting_for) = self.waiting_for(intents, &lock).await? {
+            return Err(TimelockError::NotYetUnlocked { id: lock.id, waiting_for });
+        }
+
+        let (tx, raw) = self.open(&lock, custodian_share)?;
+        self.revalidate(&lock, &tx)?;
+        let floor = oracle.gas_price(&lock.network).await?;
+        let gas_price = price_per_gas(&tx).unwrap_or_default();
+        if gas_price < floor {
+            return Err(TimelockError::GasBelowFloor { id: lock.id, gas_price, floor });
+        }
+
+        let from: Address = lock.from_address.parse().map_err(|_| self.stale(&lock, "sender is not an address"))?;
+        let account_nonce = intents.chain().transaction_count(&lock.network, from).await?;
+        let nonce = lock.nonce as u64;
+        if account_nonce > nonce {
+            let reason = format!("nonce {} used by another transaction (account at {})", nonce, account_nonce);
+            self.storage.finish_timelock(&lock.id, TIMELOCK_INVALIDATED, Some(&reason), None).await?;
+            warn!("time lock {} of {} invalidated: {}", lock.id, lock.wallet_name, reason);
+            return Err(TimelockError::NonceConsumed { id: lock.id, nonce });
+        }
+        if account_nonce < nonce {
+            return Err(TimelockError::NonceGap { id: lock.id, nonce, account_nonce });
+        }
+
+        let tx_hash = intents.send_presigned(&lock.wallet_name, &lock.network, &tx, raw).await?;
+        if !self.storage.finish_timelock(&lock.id, TIMELOCK_RELEASED, None, Some(&tx_hash)).await? {
+            warn!("time lock {} was resolved while its transaction {} was broadcast", lock.id, tx_hash);
+        }
+        info!("time lock {} of {} released as {}", lock.id, lock.wallet_name, tx_hash);
+        self.get(&lock.id).await
+    }
+
+    /// Reconstructs the key from both shares and decrypts the transaction.
+    fn open(&self, lock: &TimelockRecord, custodian_share: &str) -> Result<(TypedTransaction, Bytes), TimelockError> {
+        let invalid = || TimelockError::InvalidShare(lock.id.clone());
+        let custodian = hex::decode(custodian_share.trim().trim_start_matches("0x")).map_err(|_| invalid())?;
+        let server = hex::decode(&lock.server_share).map_err(|e| anyhow::anyhow!("corrupt server share: {}", e))?;
+        let shares: [SecretVec; 2] = [SecretVec::new(server), SecretVec::new(custodian)];
+        let data_key = combine_shares(&shares).map_err(|_| invalid())?;
+
+        let conditions =
+            UnlockConditions { unlock_at: lock.unlock_at, unlock_block: lock.unlock_block.map(|b| b as u64) };
+        let key = sealing_key(&data_key, &lock.id, &binding(&lock.network, lock.nonce as u64, &conditions))?;
+        let sealed = hex::decode(&lock.sealed_tx).map_err(|e| anyhow::anyhow!("corrupt sealed transaction: {}", e))?;
+        let raw = unseal(&key, &lock.id, &sealed).ok_or_else(invalid)?;
+        let (tx, _) = TypedTransaction::decode_signed(&Rlp::new(&raw))
+            .map_err(|e| self.stale(lock, &format!("undecodable transaction: {}", e)))?;
+        Ok((tx, Bytes::from(raw)))
+    }
+
+    fn stale(&self, lock: &TimelockRecord, reason: &str) -> TimelockError {
+        TimelockError::Stale { id: lock.id.clone(), reason: reason.to_string() }
+    }
+
+    /// The decrypted transaction is still the one that was locked, for the
+    /// chain the network is configured for, and within the gas bounds.
+    fn revalidate(&self, lock: &TimelockRecord, tx: &TypedTransaction) -> Result<(), TimelockError> {
+        let stale = |reason: String| Err(self.stale(lock, &reason));
+        let chain_id = self.chain_id(&lock.network)?;
+        match tx.chain_id().map(|id| id.as_u64()) {
+            Some(id) if id == chain_id && id as i64 == lock.chain_id => {}
+            other => return stale(format!("chain id {:?} does not match {} ({})", other, lock.network, chain_id)),
+        }
+        if tx.from().map(|from| hex_address(*from)) != Some(lock.from_address.clone()) {
+            return stale("signed by a different sender".to_string());
+        }
+        if tx.to_addr().map(|to| hex_address(*to)) != Some(lock.to_address.clone()) {
+            return stale(format!("recipient changed from {}", lock.to_address));
+        }
+        if tx.value().copied().unwrap_or_default().to_string() != lock.value {
+            return stale("value changed".to_string());
+        }
+        if tx.nonce().map(|n| n.low_u64() as i64) != Some(lock.nonce) {
+            return stale("nonce changed".to_string());
+        }
+        let gas = tx.gas().copied().unwrap_or_default();
+        if gas.is_zero() || gas > U256::from(self.config.max_gas_limit) {
+            return stale(format!("gas limit {} outside (0, {}]", gas, self.config.max_gas_limit));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_round_trip_needs_matching_binding() {
+        let data_key = [7u8; 32];
+        let conditions = UnlockConditions { unlock_at: Some(1_900_000_000), unlock_block: None };
+        let key = sealing_key(&data_key, "lock-1", &binding("eth", 70, &conditions)).unwrap();
+        let sealed = seal(&key, "lock-1", b"signed bytes").unwrap();
+        assert_eq!(unseal(&key, "lock-1", &sealed).unwrap(), b"signed bytes");
+        // another lock id authenticates differently
+        assert!(unseal(&key, "lock-2", &sealed).is_none());
+
+        // an earlier unlock date derives another key
+        let earlier = UnlockConditions { unlock_at: Some(1_800_000_000), unlock_block: None };
+        let key = sealing_key(&data_key, "lock-1", &binding("eth", 70, &earlier)).unwrap();
+        assert!(unseal(&key, "lock-1", &sealed).is_none());
+    }
+
+    #[test]
+    fn test_both_shares_rebuild_the_data_key() {
+        let data_key = [42u8; 32];
+        let shares = split_secret(&data_key, 2, 2).unwrap();
+        assert_eq!(combine_shares(&shares).unwrap().as_slice(), data_key.as_slice());
+        assert!(combine_shares(&shares[..1]).is_err());
+    }
+}
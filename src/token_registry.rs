@@ -0,0 +1,297 @@
+//! Per-token behavior flags for ERC-20 tokens that do not follow the standard,
+//! and the assets bridge transfers may name.
+//!
+//! The behavior of a token is, in order: its override stored through
+//! `/api/admin/tokens`, its entry under `tokens.behaviors` in the config, and
+//! the standard behavior. Lookups go to the database every time; they happen
+//! once per token send, next to an `eth_call` and a signature.
+//!
+//! Sends of tokens flagged `verify_delivery` register a pending delivery.
+//! When the receipt is applied ([`settle_delivery`]), the `Transfer` logs say
+//! what the recipient actually received; the delivery is settled as
+//! `delivered`, `short` (less arrived: expected for fee-on-transfer tokens,
+//! warned about otherwise) or `undelivered` (the transaction succeeded but
+//! nothing arrived), and the logged transfers are booked in the wallet's
+//! ledger with their real amounts.
+//!
+//! Bridgeable assets come from `tokens.assets` only. A request names one by
+//! symbol or contract address; [`TokenRegistry::resolve`] returns its
+//! decimals and the asset it stands for on every network, so the two legs
+//! of a transfer can be matched even where the symbols differ.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+use ethers::types::{Address, TransactionReceipt, U256};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::blockchain::erc20::{delivered_amount, TokenBehavior};
+use crate::blockchain::history::RawTransfer;
+use crate::core::config::{TokenAssetConfig, WalletConfig};
+use crate::ops::backfill::normalize;
+use crate::storage::{
+    LedgerScope, TokenBehaviorRecord, WalletStorage, DELIVERY_DELIVERED, DELIVERY_PENDING, DELIVERY_SHORT,
+    DELIVERY_UNDELIVERED,
+};
+
+/// [`LedgerScope::source`] of entries booked from a send's own receipt
+pub const RECEIPT_SOURCE: &str = "receipt";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TokenRegistryError {
+    #[error("Invalid token address: {0}")]
+    InvalidAddress(String),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+/// A token with configured or stored behavior
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenEntry {
+    pub network: String,
+    /// Lowercase contract address
+    pub token: String,
+    /// What sends use: the override when there is one, the config otherwise
+    pub behavior: TokenBehavior,
+    pub configured: Option<TokenBehavior>,
+    #[serde(rename = "override")]
+    pub stored: Option<TokenBehaviorRecord>,
+}
+
+/// A bridgeable asset as configured on one network
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolvedToken {
+    pub network: String,
+    /// Symbol the asset is configured under on `network`
+    pub symbol: String,
+    /// Uppercase asset the symbol stands for across networks, e.g. `ETH` for WETH on polygon
+    pub asset: String,
+    /// Lowercase contract address; `None` for the native coin
+    pub address: Option<String>,
+    pub decimals: u32,
+}
+
+pub struct TokenRegistry {
+    storage: Arc<WalletStorage>,
+    /// (network, lowercase address) → behavior from the config
+    configured: HashMap<(String, String), TokenBehavior>,
+    /// network → bridgeable assets from the config
+    assets: HashMap<String, Vec<ResolvedToken>>,
+}
+
+/// Lowercase `0x` form of `address`; fails on anything that is not an address.
+pub fn normalize_token(address: &str) -> Result<String, TokenRegistryError> {
+    address
+        .trim()
+        .parse::<Address>()
+        .map(|a| format!("{:?}", a))
+        .map_err(|_| TokenRegistryError::InvalidAddress(address.to_string()))
+}
+
+impl TokenRegistry {
+    /// A registry without configured tokens.
+    pub fn new(storage: Arc<WalletStorage>) -> Self {
+        Self { storage, configured: HashMap::new(), assets: HashMap::new() }
+    }
+
+    /// Fails on a configured token that is not an address.
+    pub fn from_config(config: &WalletConfig, storage: Arc<WalletStorage>) -> Result<Self, TokenRegistryError> {
+        let mut registry = Self::new(storage);
+        for (network, tokens) in &config.tokens.behaviors {
+            for (token, behavior) in tokens {
+                registry = registry.with_behavior(network, &normalize_token(token)?, *behavior);
+            }
+        }
+        for (network, assets) in &config.tokens.assets {
+            for (symbol, asset) in assets {
+                registry = registry.with_asset(network, symbol, asset)?;
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Adds `symbol` on `network` to the bridgeable assets; fails on a contract that is not an address.
+    pub fn with_asset(
+        mut self,
+        network: &str,
+        symbol: &str,
+        asset: &TokenAssetConfig,
+    ) -> Result<Self, TokenRegistryError> {
+        let address = asset.address.as_deref().map(normalize_token).transpose()?;
+        let resolved = ResolvedToken {
+            network: network.to_string(),
+            symbol: symbol.to_string(),
+            asset: asset.asset.as_deref().unwrap_or(symbol).to_ascii_uppercase(),
+            address,
+            decimals: asset.decimals,
+        };
+        let assets = self.assets.entry(network.to_string()).or_default();
+        assets.retain(|a| !a.symbol.eq_ignore_ascii_case(symbol));
+        assets.push(resolved);
+        assets.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        Ok(self)
+    }
+
+    /// The asset `token` names on `network`. An address matches the contract;
+    /// anything else matches a symbol, then the asset a symbol stands for
+    /// (`ETH` finds WETH on polygon), both ignoring case.
+    pub fn resolve(&self, network: &str, token: &str) -> Option<ResolvedToken> {
+        let assets = self.assets.get(network)?;
+        if let Ok(address) = normalize_token(token) {
+            return assets.iter().find(|a| a.address.as_deref() == Some(address.as_str())).cloned();
+        }
+        let token = token.trim();
+        assets
+            .iter()
+            .find(|a| a.symbol.eq_ignore_ascii_case(token))
+            .or_else(|| assets.iter().find(|a| a.asset.eq_ignore_ascii_case(token)))
+            .cloned()
+    }
+
+    /// Configures `token` (a lowercase address) on `network`.
+    pub fn with_behavior(mut self, network: &str, token: &str, behavior: TokenBehavior) -> Self {
+        self.configured.insert((network.to_string(), token.to_lowercase()), behavior);
+        self
+    }
+
+    /// Behavior of `token` on `network`. A stored override that cannot be
+    /// read falls back to the config, with a warning: refusing every token
+    /// send over a database hiccup would be worse.
+    pub async fn behavior(&self, network: &str, token: Address) -> TokenBehavior {
+        let token = format!("{:?}", token);
+        match self.storage.token_behavior(network, &token).await {
+            Ok(Some(record)) => return record.behavior(),
+            Ok(None) => {}
+            Err(e) => warn!("token behavior override of {} on {} unreadable: {}", token, network, e),
+        }
+        self.configured.get(&(network.to_string(), token)).copied().unwrap_or_default()
+    }
+
+    /// Every configured or overridden token, by network and address.
+    pub async fn list(&self) -> Result<Vec<TokenEntry>, TokenRegistryError> {
+        let mut entries: Vec<TokenEntry> = self
+            .configured
+            .iter()
+            .map(|((network, token), behavior)| TokenEntry {
+                network: network.clone(),
+                token: token.clone(),
+                behavior: *behavior,
+                configured: Some(*behavior),
+                stored: None,
+            })
+            .collect();
+        for record in self.storage.token_behaviors().await? {
+            match entries.iter_mut().find(|e| e.network == record.network && e.token == record.token) {
+                Some(entry) => {
+                    entry.behavior = record.behavior();
+                    entry.stored = Some(record);
+                }
+                None => entries.push(TokenEntry {
+                    network: record.network.clone(),
+                    token: record.token.clone(),
+                    behavior: record.behavior(),
+                    configured: None,
+                    stored: Some(record),
+                }),
+            }
+        }
+        entries.sort_by(|a, b| (&a.network, &a.token).cmp(&(&b.network, &b.token)));
+        Ok(entries)
+    }
+
+    /// Stores an override of `token` on `network`.
+    pub async fn set(
+        &self,
+        network: &str,
+        token: &str,
+        behavior: &TokenBehavior,
+        updated_by: &str,
+    ) -> Result<TokenBehaviorRecord, TokenRegistryError> {
+        let token = normalize_token(token)?;
+        Ok(self.storage.set_token_behavior(network, &token, behavior, updated_by).await?)
+    }
+
+    /// Drops the override of `token`; `false` if there was none.
+    pub async fn clear(&self, network: &str, token: &str) -> Result<bool, TokenRegistryError> {
+        let token = normalize_token(token)?;
+        Ok(self.storage.clear_token_behavior(network, &token).await?)
+    }
+}
+
+/// Outcome of [`settle_delivery`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryOutcome {
+    pub status: &'static str,
+    pub sent: U256,
+    pub delivered: U256,
+}
+
+/// Settles the pending delivery of the transaction behind `receipt`, if it
+/// has one, and books the token transfers of the receipt in the ledger of
+/// the sending wallet. A reverted transaction delivered nothing by design
+/// and is settled as such without a warning.
+pub async fn settle_delivery(
+    storage: &WalletStorage,
+    network: &str,
+    receipt: &TransactionReceipt,
+) -> anyhow::Result<Option<DeliveryOutcome>> {
+    let tx_hash = format!("{:?}", receipt.transaction_hash);
+    let Some(pending) = storage.token_delivery(network, &tx_hash).await? else {
+        return Ok(None);
+    };
+    if pending.status != DELIVERY_PENDING {
+        return Ok(None);
+    }
+    let token: Address = pending.token.parse().map_err(|_| anyhow::anyhow!("invalid token {}", pending.token))?;
+    let recipient: Address =
+        pending.recipient.parse().map_err(|_| anyhow::anyhow!("invalid recipient {}", pending.recipient))?;
+    let sent = U256::from_dec_str(&pending.sent).map_err(|_| anyhow::anyhow!("invalid amount {}", pending.sent))?;
+    let success = receipt.status.is_none_or(|s| s.as_u64() == 1);
+
+    let delivered = delivered_amount(&receipt.logs, token, recipient);
+    let status = if delivered >= sent {
+        DELIVERY_DELIVERED
+    } else if delivered.is_zero() {
+        DELIVERY_UNDELIVERED
+    } else {
+        DELIVERY_SHORT
+    };
+    match status {
+        DELIVERY_UNDELIVERED if success => warn!(
+            "token send {} on {} succeeded but delivered nothing to {} (sent {})",
+            tx_hash, network, pending.recipient, sent
+        ),
+        DELIVERY_SHORT if !pending.fee_on_transfer => warn!(
+            "token send {} on {} delivered {} of {}; {} is not flagged fee_on_transfer",
+            tx_hash, network, delivered, sent, pending.token
+        ),
+        DELIVERY_SHORT => {
+            info!("token send {} on {} delivered {} of {} after transfer fees", tx_hash, network, delivered, sent)
+        }
+        _ => {}
+    }
+    if !storage.settle_token_delivery(network, &tx_hash, &delivered.to_string(), status).await? {
+        // settled meanwhile by the other receipt path
+        return Ok(None);
+    }
+
+    // the block time is not on the receipt; receipts are applied right after mining
+    let timestamp = Utc::now().timestamp();
+    let sender = receipt.from;
+    let entries: Vec<_> = receipt
+        .logs
+        .iter()
+        .filter_map(|log| RawTransfer::token_transfer(log, timestamp))
+        .filter(|t| t.from == sender || t.to == Some(sender))
+        .map(|t| normalize(sender, &t))
+        .collect();
+    if !entries.is_empty() {
+        let address = format!("{:?}", sender);
+        let scope =
+            LedgerScope { wallet_name: &pending.wallet_name, network, address: &address, source: RECEIPT_SOURCE };
+        storage.record_onchain_transfers(&scope, &entries).await?;
+    }
+    Ok(Some(DeliveryOutcome { status, sent, delivered }))
+}
@@ -2,6 +2,8 @@
 //! 提供异步工具库和运行时辅助
 
 use crate::core::errors::WalletError;
+use crate::ops::incidents::spawn_named;
+use crate::util::retry::{retry, Jitter, RetryPolicy};
 use futures::future::join_all;
 use std::future::Future;
 use std::time::{Duration, Instant};
@@ -67,8 +69,11 @@ impl AsyncExecutor {
     }
 
     /// 带重试机制的异步操作
+    ///
+    /// 只重试 `WalletError::is_retryable` 的error；间隔从 `delay` 起翻倍、
+    /// 上限 30 秒，并带 equal jitter（不超过原来的间隔）。
     pub async fn retry<F, Fut, T>(
-        mut operation: F,
+        operation: F,
         max_attempts: usize,
         delay: Duration,
     ) -> AsyncResult<T>
@@ -76,34 +81,20 @@ impl AsyncExecutor {
         F: FnMut() -> Fut,
         Fut: Future<Output = AsyncResult<T>>,
     {
-        let mut current_delay = delay;
-        let mut last_error: Option<WalletError> = None;
-
-        for attempt in 1..=max_attempts {
-            match operation().await {
-                Ok(result) => return Ok(result),
-                Err(e) => {
-                    // Only retry on retryable errors
-                    if !e.is_retryable() {
-                        return Err(e);
-                    }
-                    last_error = Some(e);
-                    if attempt < max_attempts {
-                        info!(
-                            "Operation failed (attempt {}/{}). Retrying in {:?}...",
-                            attempt, max_attempts, current_delay
-                        );
-                        tokio::time::sleep(current_delay).await;
-                        // ✅ Exponential backoff with max cap (防止过长延迟)
-                        const MAX_DELAY: Duration = Duration::from_secs(30);
-                        current_delay = (current_delay * 2).min(MAX_DELAY);
-                    }
-                }
-            }
+        if max_attempts == 0 {
+            return Err(WalletError::GenericError("Retry operation failed".to_string()));
         }
-
-        Err(last_error
-            .unwrap_or_else(|| WalletError::GenericError("Retry operation failed".to_string())))
+        let policy = RetryPolicy::new()
+            .with_max_attempts(u32::try_from(max_attempts).unwrap_or(u32::MAX))
+            .with_base_delay(delay)
+            .with_max_delay(Duration::from_secs(30))
+            .with_jitter(Jitter::Equal)
+            .retry_if(WalletError::is_retryable);
+        let outcome = retry(&policy, operation).await;
+        if outcome.result.is_err() && outcome.attempts > 1 {
+            info!("Operation failed after {} attempts in {:?}", outcome.attempts, outcome.elapsed);
+        }
+        outcome.into_result()
     }
 }
 
@@ -123,7 +114,7 @@ impl<T: Send + 'static> TaskManager<T> {
     where
         F: Future<Output = AsyncResult<T>> + Send + 'static,
     {
-        let handle = tokio::spawn(future);
+        let handle = spawn_named("managed_task", future);
         self.tasks.push(handle);
     }
 
@@ -370,6 +361,48 @@ mod tests {
         assert_eq!(*attempts.lock().await, 3);
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_schedule_stays_within_previous_envelope() {
+        // 迁移前：10ms、20ms、40ms 的固定间隔；jitter 之后每次间隔落在 [d/2, d]
+        let started = tokio::time::Instant::now();
+        let starts = Arc::new(Mutex::new(Vec::new()));
+        let operation = {
+            let starts = Arc::clone(&starts);
+            move || {
+                let starts = Arc::clone(&starts);
+                async move {
+                    starts.lock().await.push(started.elapsed());
+                    Err::<(), _>(WalletError::TimeoutError("slow".to_string()))
+                }
+            }
+        };
+
+        let result = AsyncExecutor::retry(operation, 4, Duration::from_millis(10)).await;
+        assert!(matches!(result, Err(WalletError::TimeoutError(_))));
+        let starts = starts.lock().await;
+        assert_eq!(starts.len(), 4);
+        for (gap, previous) in starts.windows(2).zip([10u64, 20, 40]) {
+            let gap = gap[1] - gap[0];
+            let previous = Duration::from_millis(previous);
+            assert!(gap >= previous / 2 && gap <= previous, "gap {:?} outside envelope of {:?}", gap, previous);
+        }
+
+        // 不可重试的error立即返回
+        let attempts = Arc::new(Mutex::new(0));
+        let operation = {
+            let attempts = Arc::clone(&attempts);
+            move || {
+                let attempts = Arc::clone(&attempts);
+                async move {
+                    *attempts.lock().await += 1;
+                    Err::<(), _>(WalletError::ValidationError("bad input".to_string()))
+                }
+            }
+        };
+        assert!(AsyncExecutor::retry(operation, 4, Duration::from_millis(10)).await.is_err());
+        assert_eq!(*attempts.lock().await, 1);
+    }
+
     #[tokio::test]
     async fn test_task_manager() {
         let mut manager: TaskManager<u32> = TaskManager::new();
@@ -0,0 +1,78 @@
+//! Canonical JSON encoding for hashing and signing.
+//!
+//! Two parties that parse the same document must produce the same bytes, so
+//! the rules are fixed here rather than left to whatever map type a caller
+//! happens to hold:
+//!
+//! - object members are sorted by key, comparing the UTF-8 bytes;
+//! - no whitespace between tokens;
+//! - arrays keep their order;
+//! - strings are escaped as `serde_json` writes them (`"`, `\` and control
+//!   characters escaped, everything else including non-ASCII emitted as is);
+//! - numbers are written as `serde_json` writes them, so `2.0` stays `2.0`
+//!   and `2` stays `2`.
+//!
+//! Duplicate keys cannot survive parsing into a [`Value`]; the last one wins.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use serde_json::Value;
+
+/// The canonical encoding of `value`.
+pub fn to_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(value, &mut out);
+    out
+}
+
+fn write_value(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            // `String`'s ordering is byte-wise, which is the rule above
+            let sorted: BTreeMap<&String, &Value> = map.iter().collect();
+            out.push('{');
+            for (i, (key, value)) in sorted.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                let _ = write!(out, "{}:", Value::String(key.clone()));
+                write_value(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(item, out);
+            }
+            out.push(']');
+        }
+        scalar => {
+            let _ = write!(out, "{}", scalar);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorts_keys_by_bytes_and_drops_whitespace() {
+        let text = "{\n  \"b\": [3, 1, 2],\n  \"a\": {\"z\": true, \"Z\": null},\n  \"é\": 1, \"e\": 2\n}";
+        let value: Value = serde_json::from_str(text).unwrap();
+        // uppercase sorts before lowercase, multi-byte after ASCII
+        assert_eq!(to_string(&value), r#"{"a":{"Z":null,"z":true},"b":[3,1,2],"e":2,"é":1}"#);
+    }
+
+    #[test]
+    fn test_scalars_use_serde_json_spelling() {
+        let value: Value = serde_json::from_str(r#"{"n":[1,1.5,-0.25,2.0],"s":"quote \" slash \\ tab \t ü"}"#).unwrap();
+        assert_eq!(to_string(&value), r#"{"n":[1,1.5,-0.25,2.0],"s":"quote \" slash \\ tab \t ü"}"#);
+        assert_eq!(to_string(&Value::Null), "null");
+    }
+}
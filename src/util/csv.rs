@@ -0,0 +1,278 @@
+//! src/util/csv.rs
+//!
+//! Minimal RFC 4180 reader and writer.
+//!
+//! [`CsvReader`] is a push parser: callers feed it chunks as they arrive
+//! (e.g. from a request body stream) and get back the records completed so
+//! far, so an import never holds the raw file in memory. It accepts `,`
+//! separators, `"`-quoted fields with `""` escapes, LF or CRLF line endings
+//! and a leading UTF-8 BOM, and skips blank lines. Every record carries the
+//! line it started on, for error messages that point into the file.
+
+use std::borrow::Cow;
+
+/// Longest record [`CsvReader`] buffers before giving up on the input
+pub const DEFAULT_MAX_RECORD_LEN: usize = 64 * 1024;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("CSV line {line}: {message}")]
+pub struct CsvError {
+    /// 1-based line of the input
+    pub line: usize,
+    pub message: String,
+}
+
+impl CsvError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self { line, message: message.into() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvRecord {
+    /// 1-based line the record starts on
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    FieldStart,
+    Unquoted,
+    Quoted,
+    /// A `"` inside a quoted field: either an escape or the closing quote
+    QuoteInQuoted,
+}
+
+#[derive(Debug)]
+pub struct CsvReader {
+    state: State,
+    field: Vec<u8>,
+    fields: Vec<String>,
+    /// Bytes of the current record, for the size bound
+    record_len: usize,
+    /// Any field of the current record was quoted, so it is not a blank line
+    quoted: bool,
+    line: usize,
+    record_line: usize,
+    /// The previous chunk ended on `\r`; a leading `\n` belongs to it
+    skip_lf: bool,
+    /// Leading bytes held back until it is known whether they are a BOM
+    head: Option<Vec<u8>>,
+    max_record_len: usize,
+}
+
+impl Default for CsvReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CsvReader {
+    pub fn new() -> Self {
+        Self {
+            state: State::FieldStart,
+            field: Vec::new(),
+            fields: Vec::new(),
+            record_len: 0,
+            quoted: false,
+            line: 1,
+            record_line: 1,
+            skip_lf: false,
+            head: Some(Vec::new()),
+            max_record_len: DEFAULT_MAX_RECORD_LEN,
+        }
+    }
+
+    pub fn with_max_record_len(mut self, max_record_len: usize) -> Self {
+        self.max_record_len = max_record_len;
+        self
+    }
+
+    /// Parses `chunk` and returns the records it completed
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<CsvRecord>, CsvError> {
+        let mut records = Vec::new();
+        match self.head.take() {
+            Some(mut head) => {
+                head.extend_from_slice(chunk);
+                if head.len() < BOM.len() && BOM.starts_with(&head) {
+                    self.head = Some(head);
+                    return Ok(records);
+                }
+                let body = head.strip_prefix(BOM).unwrap_or(&head);
+                self.parse(body, &mut records)?;
+            }
+            None => self.parse(chunk, &mut records)?,
+        }
+        Ok(records)
+    }
+
+    /// Ends the input and returns its last record, if it had no trailing newline
+    pub fn finish(mut self) -> Result<Option<CsvRecord>, CsvError> {
+        let mut records = Vec::new();
+        if let Some(head) = self.head.take() {
+            // shorter than a BOM, so it cannot be one
+            self.parse(&head, &mut records)?;
+        }
+        if self.state == State::Quoted {
+            return Err(CsvError::new(self.record_line, "unterminated quoted field"));
+        }
+        self.end_record(&mut records)?;
+        Ok(records.pop())
+    }
+
+    fn parse(&mut self, bytes: &[u8], records: &mut Vec<CsvRecord>) -> Result<(), CsvError> {
+        for &byte in bytes {
+            if std::mem::take(&mut self.skip_lf) && byte == b'\n' {
+                continue;
+            }
+            self.record_len += 1;
+            if self.record_len > self.max_record_len {
+                return Err(CsvError::new(
+                    self.record_line,
+                    format!("record is longer than {} bytes", self.max_record_len),
+                ));
+            }
+            match (self.state, byte) {
+                (State::Quoted, b'"') => self.state = State::QuoteInQuoted,
+                (State::Quoted, _) => {
+                    if byte == b'\n' {
+                        self.line += 1;
+                    }
+                    self.field.push(byte);
+                }
+                (State::QuoteInQuoted, b'"') => {
+                    self.field.push(b'"');
+                    self.state = State::Quoted;
+                }
+                (State::FieldStart, b'"') => {
+                    self.quoted = true;
+                    self.state = State::Quoted;
+                }
+                (_, b',') => {
+                    self.end_field()?;
+                    self.state = State::FieldStart;
+                }
+                (_, b'\n' | b'\r') => {
+                    self.end_record(records)?;
+                    self.line += 1;
+                    self.record_line = self.line;
+                    self.skip_lf = byte == b'\r';
+                }
+                (State::QuoteInQuoted, _) => {
+                    return Err(CsvError::new(self.line, "unexpected character after closing quote"));
+                }
+                (State::Unquoted, b'"') => {
+                    return Err(CsvError::new(self.line, "quote inside an unquoted field"));
+                }
+                (State::FieldStart | State::Unquoted, _) => {
+                    self.field.push(byte);
+                    self.state = State::Unquoted;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn end_field(&mut self) -> Result<(), CsvError> {
+        let field = String::from_utf8(std::mem::take(&mut self.field))
+            .map_err(|_| CsvError::new(self.line, "field is not valid UTF-8"))?;
+        self.fields.push(field);
+        Ok(())
+    }
+
+    fn end_record(&mut self, records: &mut Vec<CsvRecord>) -> Result<(), CsvError> {
+        self.end_field()?;
+        let fields = std::mem::take(&mut self.fields);
+        let blank = !self.quoted && fields.len() == 1 && fields[0].is_empty();
+        if !blank {
+            records.push(CsvRecord { line: self.record_line, fields });
+        }
+        self.state = State::FieldStart;
+        self.quoted = false;
+        self.record_len = 0;
+        Ok(())
+    }
+}
+
+/// `field` quoted if it would not read back as-is
+pub fn escape_field(field: &str) -> Cow<'_, str> {
+    let needs_quotes = field.contains([',', '"', '\r', '\n'])
+        || field.starts_with(char::is_whitespace)
+        || field.ends_with(char::is_whitespace);
+    if needs_quotes {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Appends one record, `\n`-terminated
+pub fn write_record(out: &mut String, fields: &[&str]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&escape_field(field));
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_chunks(chunks: &[&[u8]]) -> Result<Vec<CsvRecord>, CsvError> {
+        let mut reader = CsvReader::new();
+        let mut records = Vec::new();
+        for chunk in chunks {
+            records.extend(reader.feed(chunk)?);
+        }
+        records.extend(reader.finish()?);
+        Ok(records)
+    }
+
+    fn fields(records: &[CsvRecord]) -> Vec<Vec<&str>> {
+        records.iter().map(|r| r.fields.iter().map(String::as_str).collect()).collect()
+    }
+
+    #[test]
+    fn test_quotes_crlf_and_bom_across_chunk_boundaries() {
+        let input = b"\xEF\xBB\xBFa,b\r\n\"x,\"\"y\"\"\",\"multi\nline\"\r\n\r\nlast,";
+        let whole = parse_chunks(&[input]).unwrap();
+        assert_eq!(fields(&whole), vec![vec!["a", "b"], vec!["x,\"y\"", "multi\nline"], vec!["last", ""]]);
+        assert_eq!(whole.iter().map(|r| r.line).collect::<Vec<_>>(), vec![1, 2, 5]);
+
+        // byte-at-a-time gives the same records, BOM and CRLF split included
+        let bytes: Vec<&[u8]> = input.chunks(1).collect();
+        assert_eq!(parse_chunks(&bytes).unwrap(), whole);
+    }
+
+    #[test]
+    fn test_malformed_input_reports_its_line() {
+        let err = parse_chunks(&[b"a,b\nc,d\"e\n"]).unwrap_err();
+        assert_eq!(err, CsvError::new(2, "quote inside an unquoted field"));
+        let err = parse_chunks(&[b"a\n\"open,b\nc\n"]).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.message, "unterminated quoted field");
+        let err = parse_chunks(&[b"\"a\"b\n"]).unwrap_err();
+        assert_eq!(err.line, 1);
+
+        let mut reader = CsvReader::new().with_max_record_len(8);
+        assert!(reader.feed(b"short\n").is_ok());
+        assert_eq!(reader.feed(b"much too long\n").unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn test_written_records_read_back() {
+        let rows: [&[&str]; 3] = [&["plain", "with,comma"], &["say \"hi\"", " padded "], &["", "line\nbreak"]];
+        let mut out = String::new();
+        for row in rows {
+            write_record(&mut out, row);
+        }
+        assert_eq!(fields(&parse_chunks(&[out.as_bytes()]).unwrap()), rows.map(|r| r.to_vec()).to_vec());
+        assert_eq!(escape_field("plain"), Cow::Borrowed("plain"));
+    }
+}
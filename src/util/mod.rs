@@ -0,0 +1,5 @@
+//! Small building blocks shared across subsystems
+
+pub mod canonical_json;
+pub mod csv;
+pub mod retry;
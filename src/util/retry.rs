@@ -0,0 +1,403 @@
+//! src/util/retry.rs
+//!
+//! Retry policy and combinator for operations that fail transiently.
+//!
+//! A [`RetryPolicy`] bounds the loop three ways: attempt count, per-retry
+//! backoff (exponential from `base_delay`, capped at `max_delay`, optionally
+//! jittered) and a total deadline. A classifier callback decides per error
+//! whether to retry at all, so "retry on timeout and 429, not on other 4xx"
+//! is one closure. Waiting between attempts races the policy's cancellation
+//! token, so shutdown does not sit out a backoff.
+//!
+//! Time comes from `tokio::time`; tests pause the clock instead of sleeping.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+/// How the computed backoff is randomized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jitter {
+    /// Sleep exactly the computed backoff
+    None,
+    /// Uniform in `[0, backoff]`
+    Full,
+    /// Uniform in `[backoff / 2, backoff]`: spreads callers without ever
+    /// waiting longer than the un-jittered schedule
+    Equal,
+}
+
+/// Classifier verdict for one failed attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDecision {
+    /// Retry after the policy's backoff
+    Retry,
+    /// Retry after this delay (e.g. a `Retry-After` hint), capped at `max_delay`
+    RetryAfter(Duration),
+    /// Give up and return the error
+    Stop,
+}
+
+/// Why [`retry`] returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Succeeded,
+    /// The classifier returned [`RetryDecision::Stop`]
+    NotRetryable,
+    /// `max_attempts` attempts failed
+    Exhausted,
+    /// The total deadline ran out
+    DeadlineExceeded,
+    /// The cancellation token fired while waiting to retry
+    Cancelled,
+}
+
+/// Timeouts, 429 and 5xx are worth another attempt; other statuses are not.
+pub fn http_status_decision(status: u16) -> RetryDecision {
+    match status {
+        408 | 429 | 500..=599 => RetryDecision::Retry,
+        _ => RetryDecision::Stop,
+    }
+}
+
+type Classifier<E> = Arc<dyn Fn(&E) -> RetryDecision + Send + Sync>;
+
+pub struct RetryPolicy<E> {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: Jitter,
+    deadline: Option<Duration>,
+    classifier: Classifier<E>,
+    cancel: Option<CancellationToken>,
+}
+
+impl<E> RetryPolicy<E> {
+    /// 3 attempts, 100ms doubling up to 30s, no jitter, no deadline, every
+    /// error retryable.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(30),
+            jitter: Jitter::None,
+            deadline: None,
+            classifier: Arc::new(|_| RetryDecision::Retry),
+            cancel: None,
+        }
+    }
+
+    /// Total attempts including the first; `0` is treated as `1` and
+    /// `u32::MAX` leaves the deadline as the only bound.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Backoff before the first retry; doubles for each following one
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Upper bound on the whole loop, measured from the first attempt. The
+    /// last backoff is shortened to end at the deadline, and no attempt
+    /// starts after it.
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_classifier<F>(mut self, classifier: F) -> Self
+    where
+        F: Fn(&E) -> RetryDecision + Send + Sync + 'static,
+    {
+        self.classifier = Arc::new(classifier);
+        self
+    }
+
+    /// Shorthand classifier: retry when `predicate` holds, stop otherwise
+    pub fn retry_if<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&E) -> bool + Send + Sync + 'static,
+    {
+        self.with_classifier(move |e| if predicate(e) { RetryDecision::Retry } else { RetryDecision::Stop })
+    }
+
+    /// Abort the wait between attempts once `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Un-jittered backoff before retry number `retry` (1-based)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u32.checked_shl(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    fn jittered(&self, delay: Duration) -> Duration {
+        let nanos = delay.as_nanos().min(u64::MAX as u128) as u64;
+        let nanos = match self.jitter {
+            Jitter::None => nanos,
+            Jitter::Full => rand::thread_rng().gen_range(0..=nanos),
+            Jitter::Equal => nanos / 2 + rand::thread_rng().gen_range(0..=nanos - nanos / 2),
+        };
+        Duration::from_nanos(nanos)
+    }
+}
+
+impl<E> Default for RetryPolicy<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Clone for RetryPolicy<E> {
+    fn clone(&self) -> Self {
+        Self {
+            max_attempts: self.max_attempts,
+            base_delay: self.base_delay,
+            max_delay: self.max_delay,
+            jitter: self.jitter,
+            deadline: self.deadline,
+            classifier: self.classifier.clone(),
+            cancel: self.cancel.clone(),
+        }
+    }
+}
+
+impl<E> fmt::Debug for RetryPolicy<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("jitter", &self.jitter)
+            .field("deadline", &self.deadline)
+            .field("cancellable", &self.cancel.is_some())
+            .finish()
+    }
+}
+
+/// Result of [`retry`] plus what it took, for metrics and logs
+#[derive(Debug)]
+pub struct RetryOutcome<T, E> {
+    /// The success value, or the error of the last attempt
+    pub result: Result<T, E>,
+    pub attempts: u32,
+    pub elapsed: Duration,
+    pub stop: StopReason,
+}
+
+impl<T, E> RetryOutcome<T, E> {
+    pub fn into_result(self) -> Result<T, E> {
+        self.result
+    }
+}
+
+/// Runs `op` until it succeeds or `policy` gives up. The first attempt
+/// always runs; cancellation is only observed while waiting to retry.
+pub async fn retry<T, E, F, Fut>(policy: &RetryPolicy<E>, mut op: F) -> RetryOutcome<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let started = Instant::now();
+    let mut attempts = 0u32;
+    let finish = |result, attempts, stop| RetryOutcome { result, attempts, elapsed: started.elapsed(), stop };
+    loop {
+        attempts += 1;
+        let error = match op().await {
+            Ok(value) => return finish(Ok(value), attempts, StopReason::Succeeded),
+            Err(e) => e,
+        };
+        let delay = match (policy.classifier)(&error) {
+            RetryDecision::Stop => return finish(Err(error), attempts, StopReason::NotRetryable),
+            RetryDecision::Retry => policy.jittered(policy.backoff(attempts)),
+            RetryDecision::RetryAfter(hint) => hint.min(policy.max_delay),
+        };
+        if attempts >= policy.max_attempts {
+            return finish(Err(error), attempts, StopReason::Exhausted);
+        }
+        let delay = match policy.deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_sub(started.elapsed());
+                if remaining.is_zero() {
+                    return finish(Err(error), attempts, StopReason::DeadlineExceeded);
+                }
+                delay.min(remaining)
+            }
+            None => delay,
+        };
+        debug!("attempt {}/{} failed, retrying in {:?}", attempts, policy.max_attempts, delay);
+        match &policy.cancel {
+            Some(token) => {
+                tokio::select! {
+                    biased;
+                    _ = token.cancelled() => return finish(Err(error), attempts, StopReason::Cancelled),
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
+            None => tokio::time::sleep(delay).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum FakeError {
+        Timeout,
+        Status(u16),
+    }
+
+    fn http_classifier(e: &FakeError) -> RetryDecision {
+        match e {
+            FakeError::Timeout => RetryDecision::Retry,
+            FakeError::Status(status) => http_status_decision(*status),
+        }
+    }
+
+    /// Fails with `errors` in order, then succeeds; records when each attempt started.
+    #[allow(clippy::type_complexity)]
+    fn scripted(errors: Vec<FakeError>) -> (Arc<Mutex<Vec<Duration>>>, impl FnMut() -> std::future::Ready<Result<u32, FakeError>>) {
+        let started = Instant::now();
+        let starts = Arc::new(Mutex::new(Vec::new()));
+        let log = starts.clone();
+        let mut errors = errors.into_iter();
+        let op = move || {
+            let mut log = log.lock().unwrap();
+            log.push(started.elapsed());
+            let attempt = log.len() as u32;
+            std::future::ready(errors.next().map_or(Ok(attempt), Err))
+        };
+        (starts, op)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_backoff_schedule_is_deterministic_without_jitter() {
+        let policy = RetryPolicy::new().with_max_attempts(5).with_base_delay(Duration::from_millis(100));
+        let (starts, op) = scripted(vec![FakeError::Timeout; 3]);
+        let outcome = retry(&policy, op).await;
+
+        assert_eq!(outcome.result, Ok(4));
+        assert_eq!(outcome.stop, StopReason::Succeeded);
+        assert_eq!(outcome.attempts, 4);
+        assert_eq!(outcome.elapsed, Duration::from_millis(700));
+        let ms: Vec<u128> = starts.lock().unwrap().iter().map(|d| d.as_millis()).collect();
+        assert_eq!(ms, vec![0, 100, 300, 700]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_backoff_is_capped_and_attempts_exhaust() {
+        let policy = RetryPolicy::new()
+            .with_max_attempts(4)
+            .with_base_delay(Duration::from_secs(1))
+            .with_max_delay(Duration::from_secs(3));
+        assert_eq!(policy.backoff(40), Duration::from_secs(3));
+
+        let (starts, op) = scripted(vec![FakeError::Timeout; 10]);
+        let outcome = retry(&policy, op).await;
+        assert_eq!(outcome.result, Err(FakeError::Timeout));
+        assert_eq!(outcome.stop, StopReason::Exhausted);
+        assert_eq!(outcome.attempts, 4);
+        let secs: Vec<u64> = starts.lock().unwrap().iter().map(|d| d.as_secs()).collect();
+        assert_eq!(secs, vec![0, 1, 3, 6]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_classifier_retries_timeouts_and_429_only() {
+        let policy = RetryPolicy::new().with_max_attempts(5).with_classifier(http_classifier);
+
+        let (_, op) = scripted(vec![FakeError::Timeout, FakeError::Status(429), FakeError::Status(503)]);
+        let outcome = retry(&policy, op).await;
+        assert_eq!((outcome.result, outcome.attempts), (Ok(4), 4));
+
+        for status in [400, 401, 404, 422] {
+            let (_, op) = scripted(vec![FakeError::Status(429), FakeError::Status(status)]);
+            let outcome = retry(&policy, op).await;
+            assert_eq!(outcome.result, Err(FakeError::Status(status)));
+            assert_eq!(outcome.stop, StopReason::NotRetryable);
+            assert_eq!(outcome.attempts, 2);
+        }
+
+        let hinted = RetryPolicy::new()
+            .with_max_delay(Duration::from_secs(5))
+            .with_classifier(|_: &FakeError| RetryDecision::RetryAfter(Duration::from_secs(60)));
+        let (starts, op) = scripted(vec![FakeError::Status(429)]);
+        assert_eq!(retry(&hinted, op).await.result, Ok(2));
+        assert_eq!(starts.lock().unwrap()[1], Duration::from_secs(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_deadline_shortens_last_backoff() {
+        let policy = RetryPolicy::new()
+            .with_max_attempts(u32::MAX)
+            .with_base_delay(Duration::from_millis(400))
+            .with_deadline(Duration::from_secs(1));
+        let (starts, op) = scripted(vec![FakeError::Timeout; 10]);
+        let outcome = retry(&policy, op).await;
+
+        assert_eq!(outcome.stop, StopReason::DeadlineExceeded);
+        assert_eq!(outcome.elapsed, Duration::from_secs(1));
+        let ms: Vec<u128> = starts.lock().unwrap().iter().map(|d| d.as_millis()).collect();
+        assert_eq!(ms, vec![0, 400, 1000]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancellation_aborts_backoff() {
+        let token = CancellationToken::new();
+        let policy = RetryPolicy::new()
+            .with_max_attempts(10)
+            .with_base_delay(Duration::from_secs(60))
+            .with_cancellation(token.clone());
+        let canceller = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            token.cancel();
+        });
+
+        let (_, op) = scripted(vec![FakeError::Timeout; 10]);
+        let outcome = retry(&policy, op).await;
+        canceller.await.unwrap();
+        assert_eq!(outcome.result, Err(FakeError::Timeout));
+        assert_eq!(outcome.stop, StopReason::Cancelled);
+        assert_eq!(outcome.attempts, 1);
+        assert_eq!(outcome.elapsed, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_jitter_stays_within_backoff() {
+        let base = Duration::from_millis(800);
+        let full = RetryPolicy::<FakeError>::new().with_base_delay(base).with_jitter(Jitter::Full);
+        let equal = full.clone().with_jitter(Jitter::Equal);
+        for _ in 0..200 {
+            assert!(full.jittered(base) <= base);
+            let d = equal.jittered(base);
+            assert!(d >= base / 2 && d <= base, "{:?}", d);
+        }
+    }
+}
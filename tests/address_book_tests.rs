@@ -0,0 +1,230 @@
+//! address book CSV 导入/导出：dry-run diff、部分失败与 strict、导出再导入幂等、格式错误的行号
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::Value;
+use std::sync::Arc;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::address_book::AddressBookEntry;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "address-book-admin-key";
+const WALLET: &str = "treasury";
+
+const COLD: &str = "0x52908400098527886E0F7030069857D2E4169EE7";
+const OPS: &str = "0x8617E340B3D01FA5F11F306F4090FD50E238070D";
+const VENDOR: &str = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+const VENDOR_CHECKSUMMED: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+struct Harness {
+    app: TestServer,
+    server: Arc<WalletServer>,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let server = Arc::new(
+        WalletServer::new_for_test(
+            "127.0.0.1".to_string(),
+            0,
+            config,
+            Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+            None,
+        )
+        .await
+        .unwrap(),
+    );
+    server.wallet_manager.create_wallet(WALLET, "Addr3ss!Book#2024", false).await.unwrap();
+    let app = TestServer::new((*server).clone().create_router().await).unwrap();
+    Harness { app, server, _dir: dir }
+}
+
+fn entry(network: &str, address: &str, label: &str, tags: &[&str]) -> AddressBookEntry {
+    AddressBookEntry {
+        network: network.to_string(),
+        address: address.to_string(),
+        label: label.to_string(),
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+    }
+}
+
+impl Harness {
+    async fn seed(&self) {
+        let seeded = [entry("eth", COLD, "Cold storage", &["internal"]), entry("eth", OPS, "Ops", &[])];
+        self.server.storage.upsert_address_book(WALLET, &seeded).await.unwrap();
+    }
+
+    async fn import(&self, csv: &str, query: &[(&str, &str)], status: StatusCode) -> Value {
+        let mut request =
+            self.app.post(&format!("/api/wallets/{}/address_book/import", WALLET)).add_header("X-API-KEY", API_KEY);
+        for (key, value) in query {
+            request = request.add_query_param(key, value);
+        }
+        let res = request.text(csv.to_string()).await;
+        res.assert_status(status);
+        res.json()
+    }
+
+    async fn export(&self) -> String {
+        let res = self
+            .app
+            .get(&format!("/api/wallets/{}/address_book/export", WALLET))
+            .add_header("X-API-KEY", API_KEY)
+            .await;
+        res.assert_status_ok();
+        assert_eq!(res.header("content-type"), "text/csv; charset=utf-8");
+        res.text()
+    }
+}
+
+fn lines(rows: &Value) -> Vec<u64> {
+    rows.as_array().unwrap().iter().map(|row| row["line"].as_u64().unwrap()).collect()
+}
+
+/// 第 6 行的 EVM address 声称在 btc 上
+fn mixed_csv() -> String {
+    format!(
+        "label,address,network,tags\n\
+         Cold storage,{cold_lower},ethereum,internal\n\
+         Operations,{ops},eth,\n\
+         Vendor,{vendor},polygon,payables;monthly\n\
+         Vendor again,{vendor},polygon,\n\
+         Wrong chain,{ops},btc,\n",
+        cold_lower = COLD.to_lowercase(),
+        ops = OPS,
+        vendor = VENDOR,
+    )
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_dry_run_diff_against_seeded_book() {
+    let h = build().await;
+    h.seed().await;
+    let before = h.export().await;
+
+    let diff = h.import(&mixed_csv(), &[("dry_run", "true")], StatusCode::OK).await;
+    assert_eq!(diff["dry_run"], true);
+    assert_eq!(diff["applied"], false);
+    assert_eq!(
+        diff["counts"],
+        serde_json::json!({ "would_add": 1, "would_update_label": 1, "would_skip_duplicate": 2, "invalid": 1 })
+    );
+
+    assert_eq!(lines(&diff["would_add"]), vec![4]);
+    assert_eq!(diff["would_add"][0]["address"], VENDOR_CHECKSUMMED);
+    assert_eq!(diff["would_add"][0]["tags"], serde_json::json!(["monthly", "payables"]));
+
+    assert_eq!(lines(&diff["would_update_label"]), vec![3]);
+    assert_eq!(diff["would_update_label"][0]["label"], "Operations");
+    assert_eq!(diff["would_update_label"][0]["previous_label"], "Ops");
+
+    // 小写写法与已存的校验和形式是同一个 address
+    assert_eq!(lines(&diff["would_skip_duplicate"]), vec![2, 5]);
+    assert_eq!(diff["would_skip_duplicate"][0]["reason"], "already in address book");
+    assert_eq!(diff["would_skip_duplicate"][1]["reason"], "repeats line 4");
+
+    assert_eq!(lines(&diff["invalid"]), vec![6]);
+    assert!(diff["invalid"][0]["reason"].as_str().unwrap().contains("not usable on btc"), "{}", diff["invalid"]);
+
+    assert_eq!(h.export().await, before);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_invalid_rows_are_skipped_unless_strict() {
+    let h = build().await;
+    h.seed().await;
+    let before = h.export().await;
+
+    let rejected = h.import(&mixed_csv(), &[("strict", "true")], StatusCode::UNPROCESSABLE_ENTITY).await;
+    assert_eq!(rejected["applied"], false);
+    assert_eq!(rejected["counts"]["invalid"], 1);
+    assert_eq!(h.export().await, before);
+
+    let applied = h.import(&mixed_csv(), &[], StatusCode::OK).await;
+    assert_eq!(applied["applied"], true);
+    assert_eq!(applied["counts"]["invalid"], 1);
+    let book = h.server.storage.address_book(WALLET).await.unwrap();
+    assert_eq!(
+        book,
+        vec![
+            entry("eth", COLD, "Cold storage", &["internal"]),
+            entry("eth", OPS, "Operations", &[]),
+            entry("polygon", VENDOR_CHECKSUMMED, "Vendor", &["monthly", "payables"]),
+        ]
+    );
+    // the rejected strict attempt is audited too, as not applied
+    let audit = h.server.storage.get_audit_logs(Some(WALLET)).await.unwrap();
+    let mut applied: Vec<bool> = audit
+        .iter()
+        .filter(|log| log.action == "address_book_import")
+        .map(|log| serde_json::from_str::<Value>(log.details.as_deref().unwrap()).unwrap()["applied"].as_bool().unwrap())
+        .collect();
+    applied.sort();
+    assert_eq!(applied, vec![false, true]);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_export_import_round_trip_is_idempotent() {
+    let h = build().await;
+    // 10k 行，流式解析
+    let mut csv = String::from("address,network,label\n");
+    for i in 1..=10_000u32 {
+        csv.push_str(&format!("0x{:040x},{},\"Payee, #{}\"\n", i, if i % 2 == 0 { "eth" } else { "bsc" }, i));
+    }
+    let imported = h.import(&csv, &[], StatusCode::OK).await;
+    assert_eq!(imported["counts"]["would_add"], 10_000);
+
+    let exported = h.export().await;
+    let first_row = "\"Payee, #1\",0x0000000000000000000000000000000000000001,bsc,\n";
+    assert!(exported.starts_with(&format!("label,address,network,tags\n{}", first_row)), "{}", &exported[..200]);
+    assert_eq!(exported.lines().count(), 10_001);
+
+    let again = h.import(&exported, &[], StatusCode::OK).await;
+    assert_eq!(again["applied"], false);
+    assert_eq!(again["counts"]["would_skip_duplicate"], 10_000);
+    assert_eq!(again["counts"]["would_add"], 0);
+    assert_eq!(again["counts"]["would_update_label"], 0);
+    assert_eq!(h.export().await, exported);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_malformed_csv_reports_offending_line() {
+    let h = build().await;
+    let csv = format!("label,address,network\nCold,{},eth\nBroken \"quote,{},eth\n", COLD, OPS);
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/address_book/import", WALLET))
+        .add_header("X-API-KEY", API_KEY)
+        .text(csv)
+        .await;
+    res.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "CSV_MALFORMED");
+    assert!(body["error"]["message"].as_str().unwrap().contains("line 3"), "{}", body);
+    assert!(h.server.storage.address_book(WALLET).await.unwrap().is_empty());
+
+    let res = h
+        .app
+        .post("/api/wallets/nobody/address_book/import")
+        .add_header("X-API-KEY", API_KEY)
+        .text("label,address,network\n")
+        .await;
+    res.assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,97 @@
+//! address识别端点与发送时的跨链误用提示
+
+mod util;
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+
+const API_KEY: &str = "address-validation-admin-key";
+const OWNER: &str = "address-validation-owner-token";
+const WALLET: &str = "support-desk";
+const MANAGED: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+const BTC_TESTNET: &str = "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7";
+
+async fn build() -> (TestServer, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY)).await;
+    let user_id = util::sign_in(&server, "support@example.com", OWNER).await;
+    server.user_db.link_wallet(&user_id, WALLET, &MANAGED.to_lowercase(), None).await.unwrap();
+    (TestServer::new(server.create_router().await).unwrap(), dir)
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_validate_address_endpoint() {
+    let (app, _dir) = build().await;
+
+    app.get("/api/validate/address").add_query_param("value", MANAGED).await.assert_status_unauthorized();
+
+    // admin 看到wallet名
+    let res = app
+        .get("/api/validate/address")
+        .add_header("X-API-KEY", API_KEY)
+        .add_query_param("value", MANAGED.to_lowercase())
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["valid"], true);
+    let m = &body["matches"][0];
+    assert_eq!(m["family"], "evm");
+    assert_eq!(m["normalized"], MANAGED);
+    assert_eq!(m["checksum"], "absent");
+    assert_eq!((m["managed"].clone(), m["wallet_name"].clone()), (json!(true), json!(WALLET)));
+
+    // 会话user只得到布尔值
+    let res = app
+        .get("/api/validate/address")
+        .add_header("Authorization", format!("Bearer {}", OWNER))
+        .add_query_param("value", MANAGED)
+        .await;
+    res.assert_status_ok();
+    let m = res.json::<Value>()["matches"][0].clone();
+    assert_eq!(m["managed"], true);
+    assert!(m.get("wallet_name").is_none());
+
+    let res = app
+        .get("/api/validate/address")
+        .add_header("X-API-KEY", API_KEY)
+        .add_query_param("value", BTC_TESTNET)
+        .await;
+    let m = res.json::<Value>()["matches"][0].clone();
+    assert_eq!((m["family"].clone(), m["network"].clone()), (json!("bitcoin"), json!("testnet")));
+    assert_eq!(m["managed"], false);
+    assert_eq!(m["warnings"].as_array().unwrap().len(), 1);
+
+    let res = app
+        .get("/api/validate/address")
+        .add_header("X-API-KEY", API_KEY)
+        .add_query_param("value", "x".repeat(10_000))
+        .await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>(), json!({ "value": "x".repeat(10_000), "valid": false, "matches": [] }));
+
+    // 非法 UTF-8 的百分号编码与缺失参数都是 400，不会 panic
+    let res = app.get("/api/validate/address?value=%FF%FE%80").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_bad_request();
+    let res = app.get("/api/validate/address?value=%20").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "MISSING_PARAMETER");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_send_to_bitcoin_address_explains_misuse() {
+    let (app, _dir) = build().await;
+    let res = app
+        .post(&format!("/api/wallets/{}/send", WALLET))
+        .add_header("Authorization", format!("Bearer {}", OWNER))
+        .json(&json!({ "to": BTC_TESTNET, "amount": "0.1", "network": "eth", "password": "irrelevant" }))
+        .await;
+    res.assert_status_bad_request();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "WRONG_CHAIN_ADDRESS");
+    assert_eq!(
+        body["error"]["message"],
+        "This looks like a Bitcoin testnet address; an EVM address (0x + 40 hex digits) is required"
+    );
+}
@@ -0,0 +1,109 @@
+//! `/api/admin/jobs`：后台任务状态与手动触发
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::ops::jobs::{Job, Schedule};
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "admin-jobs-test-key-0123456789";
+
+/// 每小时一次，启动时不运行
+#[derive(Default)]
+struct CountingJob {
+    runs: AtomicUsize,
+}
+
+#[async_trait]
+impl Job for CountingJob {
+    fn name(&self) -> &str {
+        "counter"
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: Duration::from_secs(3600), immediate: false }
+    }
+
+    async fn run(&self, _cancel: CancellationToken) -> anyhow::Result<()> {
+        self.runs.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_admin_jobs_list_and_manual_run() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    let job = Arc::new(CountingJob::default());
+    server.jobs.register(job.clone());
+    server.jobs.start();
+    let jobs = server.jobs.clone();
+    let maintenance = server.maintenance.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    app.get("/api/admin/jobs").await.assert_status_unauthorized();
+    app.post("/api/admin/jobs/counter/run").await.assert_status_unauthorized();
+
+    let res = app.get("/api/admin/jobs").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["healthy"], true);
+    assert_eq!(body["jobs"][0]["name"], "counter");
+    assert_eq!(body["jobs"][0]["schedule"], "every 3600s");
+    assert_eq!(body["jobs"][0]["runs"], 0);
+    assert_eq!(body["jobs"][0]["alive"], true);
+
+    // 手动触发不等调度
+    let res = app.post("/api/admin/jobs/counter/run").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    let state: Value = res.json();
+    assert_eq!(state["runs"], 1);
+    assert!(state["last_success_at"].is_i64());
+    assert_eq!(job.runs.load(Ordering::SeqCst), 1);
+
+    let res = app.post("/api/admin/jobs/nope/run").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_not_found();
+    assert_eq!(res.json::<Value>()["error"]["code"], "JOB_NOT_FOUND");
+
+    maintenance.enable("upgrade");
+    let res = app.post("/api/admin/jobs/counter/run").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status(axum::http::StatusCode::CONFLICT);
+    assert_eq!(res.json::<Value>()["error"]["code"], "MAINTENANCE_MODE");
+    maintenance.disable();
+
+    let res = app.get("/api/admin/summary").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["jobs"][0]["runs"], 1);
+
+    assert!(jobs.shutdown(Duration::from_secs(1)).await);
+    let body: Value = app.get("/api/admin/jobs").add_header("X-API-KEY", API_KEY).await.json();
+    assert_eq!(body["jobs"][0]["alive"], false);
+    assert_eq!(body["healthy"], false);
+}
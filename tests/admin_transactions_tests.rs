@@ -0,0 +1,174 @@
+//! `/api/admin/transactions` 跨wallet查询与 recheck 集成测试
+//!
+//! recheck 使用 MockProvider 支撑的 EthereumClient，不访问真实 RPC。
+
+use axum_test::TestServer;
+use chrono::Utc;
+use ethers::providers::{MockProvider, Provider};
+use ethers::types::{TransactionReceipt, U64};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::TransactionRecord;
+
+const API_KEY: &str = "admin-transactions-test-key-0123456789";
+
+fn tx_hash(i: usize) -> String {
+    format!("0x{:064x}", i + 1)
+}
+
+async fn build_server() -> WalletServer {
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+
+    // wallet -> (network, status, age_secs)
+    let seeds = [
+        ("ops_a", "eth", "pending", 3600),
+        ("ops_a", "eth", "confirmed", 3600),
+        ("ops_b", "eth", "pending", 60),
+        ("ops_b", "polygon", "pending", 1800),
+        ("ops_c", "eth", "failed", 7200),
+    ];
+    for name in ["ops_a", "ops_b", "ops_c"] {
+        server.storage.store_wallet(name, b"blob", false).await.unwrap();
+    }
+    let wallets = server.storage.list_wallets().await.unwrap();
+    for (i, (wallet, network, status, age)) in seeds.iter().enumerate() {
+        let wallet_id = wallets.iter().find(|w| w.name == *wallet).unwrap().id.clone();
+        server
+            .storage
+            .store_transaction(&TransactionRecord {
+                id: format!("ops-tx-{}", i),
+                wallet_id,
+                tx_hash: tx_hash(i),
+                network: network.to_string(),
+                from_address: "0x1234567890123456789012345678901234567890".to_string(),
+                to_address: "0x0987654321098765432109876543210987654321".to_string(),
+                amount: "1.0".to_string(),
+                fee: "0.001".to_string(),
+                status: status.to_string(),
+                created_at: Utc::now() - chrono::Duration::seconds(*age),
+                confirmed_at: None,
+                integrity_hash: String::new(),
+                fee_actual: None,
+                block_number: None,
+            })
+            .await
+            .unwrap();
+    }
+    server
+}
+
+#[tokio::test]
+async fn test_admin_transactions_requires_api_key() {
+    let app = TestServer::new(build_server().await.create_router().await).unwrap();
+    let res = app.get("/api/admin/transactions").await;
+    res.assert_status_unauthorized();
+}
+
+#[tokio::test]
+async fn test_admin_transactions_filters_and_paginates() {
+    let app = TestServer::new(build_server().await.create_router().await).unwrap();
+
+    let body: Value = app
+        .get("/api/admin/transactions?status=pending&older_than=600")
+        .add_header("X-API-KEY", API_KEY)
+        .await
+        .json();
+    assert_eq!(body["total"], 2);
+    let ids: Vec<&str> =
+        body["transactions"].as_array().unwrap().iter().map(|t| t["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, vec!["ops-tx-0", "ops-tx-3"]);
+    assert!(body["transactions"][0].get("encrypted_data").is_none());
+
+    let body: Value = app
+        .get("/api/admin/transactions?status=pending,failed&network=eth")
+        .add_header("X-API-KEY", API_KEY)
+        .await
+        .json();
+    assert_eq!(body["total"], 3);
+
+    let body: Value = app
+        .get("/api/admin/transactions?page=2&page_size=2")
+        .add_header("X-API-KEY", API_KEY)
+        .await
+        .json();
+    assert_eq!(body["total"], 5);
+    assert_eq!(body["page"], 2);
+    assert_eq!(body["transactions"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_recheck_transitions_confirmed_transactions() {
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    let receipt = TransactionReceipt { status: Some(U64::from(1)), ..Default::default() };
+    mock.push(receipt).unwrap();
+    let client = EthereumClient::new_with_provider_and_chain(provider, "eth", 1);
+
+    let server = build_server()
+        .await
+        .with_chain_clients(ClientRegistry::new().with_client("eth", Arc::new(client)));
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let res = app
+        .post("/api/admin/transactions/recheck")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "status": "pending", "network": "eth", "older_than": 600 }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["checked"], 1);
+    assert_eq!(body["transitioned"], 1);
+    assert_eq!(body["errors"], 0);
+
+    let body: Value = app
+        .get("/api/admin/transactions?status=confirmed&network=eth")
+        .add_header("X-API-KEY", API_KEY)
+        .await
+        .json();
+    assert_eq!(body["total"], 2);
+    let rechecked = body["transactions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["id"] == "ops-tx-0")
+        .expect("ops-tx-0 confirmed");
+    assert!(rechecked["confirmed_at"].is_string());
+}
+
+#[tokio::test]
+async fn test_recheck_without_client_reports_errors() {
+    let server = build_server().await.with_chain_clients(ClientRegistry::new());
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let body: Value = app
+        .post("/api/admin/transactions/recheck")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "status": "pending", "limit": 10 }))
+        .await
+        .json();
+    assert_eq!(body["checked"], 3);
+    assert_eq!(body["transitioned"], 0);
+    assert_eq!(body["errors"], 3);
+}
@@ -0,0 +1,220 @@
+//! 准入控制：突发请求下签名并发不超过上限、单个wallet的突发不占满全局额度、
+//! interactive 优先于排队中的 batch、队列满时 429 + Retry-After，
+//! 以及超过 deadline 的等待者被移出队列、drain 时排队请求立即失败
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{body::Body, http::Request, http::StatusCode, routing::post, Router};
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use tower::ServiceExt;
+
+use defi_hot_wallet::api::admission::{AdmissionController, AdmissionError, Lane};
+use defi_hot_wallet::core::config::AdmissionConfig;
+
+fn controller(max_in_flight: usize, per_wallet_in_flight: usize, max_queued: usize) -> Arc<AdmissionController> {
+    Arc::new(AdmissionController::new(AdmissionConfig {
+        max_in_flight,
+        per_wallet_in_flight,
+        max_queued,
+        per_wallet_queued: max_queued,
+        max_wait_ms: 60_000,
+        retry_after_secs: 7,
+    }))
+}
+
+fn deadline() -> Instant {
+    Instant::now() + Duration::from_secs(60)
+}
+
+/// 记录同时在签名中的数量及其峰值
+#[derive(Default)]
+struct FakeSigner {
+    in_flight: AtomicUsize,
+    peak: AtomicUsize,
+    signed: Mutex<Vec<String>>,
+}
+
+impl FakeSigner {
+    async fn sign(&self, wallet: &str) {
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        self.signed.lock().push(wallet.to_string());
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_concurrency_cap_holds_under_burst() {
+    let admission = controller(4, 4, 256);
+    let signer = Arc::new(FakeSigner::default());
+    let tasks: Vec<_> = (0..200)
+        .map(|i| {
+            let (admission, signer) = (admission.clone(), signer.clone());
+            tokio::spawn(async move {
+                let wallet = format!("wallet-{}", i % 10);
+                let _permit = admission.acquire(Some(&wallet), Lane::Interactive, deadline()).await?;
+                signer.sign(&wallet).await;
+                Ok::<_, AdmissionError>(())
+            })
+        })
+        .collect();
+    let results: Vec<_> = futures::future::join_all(tasks).await.into_iter().map(Result::unwrap).collect();
+
+    // 队列容量 256 足够，全部请求最终都被执行，且同时签名的数量从未超过 4
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(signer.signed.lock().len(), 200);
+    assert_eq!(signer.peak.load(Ordering::SeqCst), 4);
+    let snapshot = admission.snapshot();
+    assert_eq!((snapshot.in_flight, snapshot.queued, snapshot.saturated), (0, 0, false));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_one_wallet_burst_cannot_take_the_global_budget() {
+    let admission = controller(4, 2, 64);
+    let hot = (0..2).map(|_| {
+        let admission = admission.clone();
+        async move { admission.acquire(Some("hot"), Lane::Interactive, deadline()).await.unwrap() }
+    });
+    let hot_permits = futures::future::join_all(hot).await;
+
+    // 同一wallet再来的请求排队，即使全局还有空位
+    let queued_hot = {
+        let admission = admission.clone();
+        tokio::spawn(async move { admission.acquire(Some("hot"), Lane::Interactive, deadline()).await.map(drop) })
+    };
+    tokio::task::yield_now().await;
+    assert_eq!(admission.snapshot().queued, 1);
+    assert_eq!(admission.snapshot().in_flight, 2);
+
+    // 其他wallet不受影响，直接获得剩余的全局额度
+    let cold = admission.acquire(Some("cold"), Lane::Interactive, deadline());
+    let cold = tokio::time::timeout(Duration::from_millis(1), cold)
+        .await
+        .expect("cold wallet must not wait behind the hot wallet")
+        .unwrap();
+    let other = admission.acquire(Some("other"), Lane::Batch, deadline()).await.unwrap();
+    assert_eq!(admission.snapshot().in_flight, 4);
+    assert!(!queued_hot.is_finished());
+
+    drop((cold, other));
+    tokio::task::yield_now().await;
+    // 全局有空位但 hot 仍占满自己的份额
+    assert!(!queued_hot.is_finished());
+    drop(hot_permits);
+    assert_eq!(queued_hot.await.unwrap(), Ok(()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_interactive_requests_jump_queued_batch_work() {
+    let admission = controller(1, 1, 16);
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let held = admission.acquire(None, Lane::Batch, deadline()).await.unwrap();
+
+    let spawn = |label: &'static str, lane: Lane| {
+        let (admission, order) = (admission.clone(), order.clone());
+        tokio::spawn(async move {
+            let _permit = admission.acquire(None, lane, deadline()).await.unwrap();
+            order.lock().push(label);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        })
+    };
+    // batch 先到，interactive 后到
+    let mut tasks = Vec::new();
+    for (label, lane) in [("batch-1", Lane::Batch), ("batch-2", Lane::Batch), ("send-1", Lane::Interactive)] {
+        tasks.push(spawn(label, lane));
+        tokio::task::yield_now().await;
+    }
+    tasks.push(spawn("send-2", Lane::Interactive));
+    tokio::task::yield_now().await;
+    assert_eq!(admission.snapshot().queued, 4);
+
+    drop(held);
+    futures::future::join_all(tasks).await;
+    // 同一 lane 内按到达顺序
+    assert_eq!(*order.lock(), ["send-1", "send-2", "batch-1", "batch-2"]);
+}
+
+#[tokio::test]
+async fn test_queue_overflow_returns_429_with_retry_after() {
+    let admission = controller(1, 1, 1);
+    let release = Arc::new(Notify::new());
+    let entered = Arc::new(Notify::new());
+    let handler = {
+        let (release, entered) = (release.clone(), entered.clone());
+        post(move || async move {
+            entered.notify_one();
+            release.notified().await;
+            "signed"
+        })
+    };
+    let app: Router = Router::new()
+        .route("/api/wallets/:name/send", admission.route(Lane::Interactive, Duration::from_secs(30)).wrap(handler));
+    let send = |wallet: &str| {
+        let request = Request::post(format!("/api/wallets/{}/send", wallet)).body(Body::empty()).unwrap();
+        tokio::spawn(app.clone().oneshot(request))
+    };
+
+    let running = send("treasury");
+    entered.notified().await;
+    let queued = send("payroll");
+    while admission.snapshot().queued == 0 {
+        tokio::task::yield_now().await;
+    }
+    let res = send("ops").await.unwrap().unwrap();
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(res.headers()["retry-after"], "7");
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "SERVER_BUSY");
+
+    release.notify_one();
+    assert_eq!(running.await.unwrap().unwrap().status(), StatusCode::OK);
+    entered.notified().await;
+    release.notify_one();
+    assert_eq!(queued.await.unwrap().unwrap().status(), StatusCode::OK);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_expired_and_drained_waiters_leave_the_queue() {
+    let admission = controller(1, 1, 8);
+    let held = admission.acquire(Some("w"), Lane::Interactive, deadline()).await.unwrap();
+
+    // 等待计入 deadline，超时后从队列中移除，不会在之后占用槽位
+    let start = Instant::now();
+    let err = admission.acquire(Some("w"), Lane::Interactive, start + Duration::from_millis(250)).await.unwrap_err();
+    assert_eq!(err, AdmissionError::QueueTimeout);
+    assert!(start.elapsed() >= Duration::from_millis(250));
+    assert_eq!(admission.snapshot().queued, 0);
+
+    // 调用方放弃（如外层请求超时）同样移出队列
+    let abandoned = admission.acquire(None, Lane::Batch, deadline());
+    assert!(tokio::time::timeout(Duration::from_millis(10), abandoned).await.is_err());
+    assert_eq!(admission.snapshot().queued, 0);
+
+    // drain：排队的请求立即失败，而不是等到 deadline
+    let waiters: Vec<_> = (0..3)
+        .map(|_| {
+            let admission = admission.clone();
+            tokio::spawn(async move { admission.acquire(None, Lane::Batch, deadline()).await.map(drop) })
+        })
+        .collect();
+    while admission.snapshot().queued < 3 {
+        tokio::task::yield_now().await;
+    }
+    let drained_at = Instant::now();
+    admission.drain();
+    for waiter in waiters {
+        assert_eq!(waiter.await.unwrap(), Err(AdmissionError::ShuttingDown));
+    }
+    assert_eq!(drained_at.elapsed(), Duration::ZERO);
+
+    // 已获准的操作继续执行直到完成
+    assert_eq!(admission.snapshot().in_flight, 1);
+    drop(held);
+    assert_eq!(admission.snapshot().in_flight, 0);
+}
@@ -0,0 +1,132 @@
+//! 精确金额：超过 f64 精度的金额走完整发送与记录路径后仍然精确，
+//! 以及禁止在金额路径上用 f64 解析用户输入的源码检查
+
+use async_trait::async_trait;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, Transaction, TransactionRequest, H256, U256};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::core::amount::Amount;
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::{BroadcastChain, SigningIntentLog};
+use defi_hot_wallet::monitoring::WalletMetrics;
+use defi_hot_wallet::storage::WalletStorage;
+
+const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+/// 节点：记下收到的原始transaction
+#[derive(Default)]
+struct MockChain {
+    received: Mutex<Vec<Bytes>>,
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        tx.set_nonce(self.received.lock().unwrap().len() as u64);
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(1_000_000_007u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        let hash = H256::from(ethers::utils::keccak256(&raw));
+        self.received.lock().unwrap().push(raw);
+        Ok(hash)
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+}
+
+#[tokio::test]
+async fn test_value_above_2_pow_53_survives_send_and_record() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display());
+    // 落库wallet写审计记录，其 MAC 需要 WALLET_ENC_KEY
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let storage = Arc::new(WalletStorage::new_with_url(&url).await.unwrap());
+    storage.store_wallet("hot", b"blob", false).await.unwrap();
+    let chain = Arc::new(MockChain::default());
+    let metrics = Arc::new(WalletMetrics::new().unwrap());
+    let log = SigningIntentLog::new(storage.clone(), chain.clone()).with_metrics(metrics.clone());
+    let signer: LocalWallet = KEY.parse().unwrap();
+
+    // 2^53 + 1 wei，f64 表示不了
+    let amount = Amount::parse_native("eth", "0.009007199254740993").unwrap();
+    assert_eq!(amount.minimal(), (1u128 << 53) + 1);
+    let tx: TypedTransaction = TransactionRequest::new()
+        .to("0x000000000000000000000000000000000000dEaD".parse::<Address>().unwrap())
+        .value(amount.to_u256())
+        .into();
+    let tx_hash = log.send("hot", "eth", &signer, tx).await.unwrap();
+
+    // 广播的值精确
+    let raw = chain.received.lock().unwrap()[0].clone();
+    let broadcast: Transaction = ethers::utils::rlp::decode(&raw).unwrap();
+    assert_eq!(broadcast.value, U256::from((1u64 << 53) + 1));
+    assert_eq!(broadcast.recover_from().unwrap(), signer.address());
+
+    // 记录的金额精确，并能无损读回
+    let wallet_id = storage.wallet_id("hot").await.unwrap().unwrap();
+    let recorded = storage.get_wallet_transactions(&wallet_id).await.unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].tx_hash, tx_hash);
+    assert_eq!(recorded[0].amount, "0.009007199254740993");
+    assert_eq!(Amount::parse_native("eth", &recorded[0].amount).unwrap(), amount);
+
+    let exported = metrics.export_metrics().unwrap();
+    assert!(exported.contains("transactions_sent_total 1"), "{}", exported);
+}
+
+/// 允许的 f64 解析：不是链上金额，或是返回 f64 的旧接口
+const F64_PARSE_ALLOWLIST: &[(&str, &str)] = &[
+    ("src/api/bridge/lifi.rs", "sums USD fiat estimates returned by LI.FI"),
+    ("src/api/bridge_lifi.rs", "sums USD fiat estimates returned by LI.FI"),
+    ("src/mvp.rs", "legacy f64 MVP helpers"),
+    ("src/blockchain/blockchain_ethereum_tests.rs", "test-only display check"),
+];
+
+fn rust_files(dir: &Path, out: &mut Vec<std::path::PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            rust_files(&path, out);
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            out.push(path);
+        }
+    }
+}
+
+#[test]
+fn test_no_f64_parsing_of_amounts_in_source_tree() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut files = Vec::new();
+    rust_files(&root.join("src"), &mut files);
+    assert!(!files.is_empty());
+
+    let mut offenders = Vec::new();
+    for path in files {
+        let relative = path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/");
+        if F64_PARSE_ALLOWLIST.iter().any(|(file, _)| *file == relative) {
+            continue;
+        }
+        let source = std::fs::read_to_string(&path).unwrap();
+        for (no, line) in source.lines().enumerate() {
+            let code = line.split("//").next().unwrap_or_default();
+            let typed_parse = code.contains(": f64 =") && code.contains(".parse()");
+            if code.contains("parse::<f64>") || typed_parse {
+                offenders.push(format!("{}:{}: {}", relative, no + 1, line.trim()));
+            }
+        }
+    }
+    assert!(
+        offenders.is_empty(),
+        "parse amounts with core::amount::Amount instead of f64:\n{}",
+        offenders.join("\n")
+    );
+}
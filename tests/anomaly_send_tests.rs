@@ -0,0 +1,256 @@
+//! 服务端sign发送前的异常检测：High 及以上且 `block_on_high` 打开时 403 `ANOMALY_BLOCKED`
+//! 且不sign；关闭时照常发送，分数写入审计日志。批量发送同样检测，Password错误时不检测
+
+mod util;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionReceipt, H256};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::anomaly_detection::{
+    AnomalyDetectionConfig, AnomalyDetector, RecommendedAction, RulePlugin, RuleResult, ThreatLevel,
+    TransactionContext,
+};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::storage::WalletStorage;
+
+const API_KEY: &str = "anomaly-send-admin-key-0123456789";
+const SESSION: &str = "anomaly-send-session-token";
+const WALLET: &str = "screened";
+const PASSWORD: &str = "Scr33ned!Vault#2024";
+const TO: &str = "0x5aeda56215b167893e80b4fe645ba6d5bab767de";
+
+/// 节点：只数广播了几笔
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    sent: Mutex<usize>,
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        *self.sent.lock().unwrap() += 1;
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_count(&self, _network: &str, _address: Address) -> Result<u64, WalletError> {
+        Ok(*self.nonce.lock().unwrap())
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        _tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        Ok(None)
+    }
+}
+
+/// 向wallet从未付过款的address转出超过阈值即判 High
+struct LargeToNewRecipient {
+    threshold: f64,
+}
+
+impl RulePlugin for LargeToNewRecipient {
+    fn name(&self) -> &str {
+        "large_to_new_recipient"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn description(&self) -> &str {
+        "大额转给新收款方"
+    }
+
+    fn evaluate(&self, context: &TransactionContext) -> RuleResult {
+        if context.features.is_new_address > 0.5 && context.amount > self.threshold {
+            return RuleResult::threat(
+                ThreatLevel::High,
+                format!("{:.2} to a never-paid recipient", context.amount),
+                0.9,
+                RecommendedAction::Block,
+            );
+        }
+        RuleResult::no_threat()
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    chain: Arc<MockChain>,
+    storage: Arc<WalletStorage>,
+    _dir: tempfile::TempDir,
+}
+
+async fn build(block_on_high: bool) -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let detector = AnomalyDetector::with_config(AnomalyDetectionConfig { block_on_high, ..Default::default() });
+    detector.plugin_registry().register(Arc::new(LargeToNewRecipient { threshold: 1.0 })).unwrap();
+    let chain = Arc::new(MockChain::default());
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY))
+        .await
+        .with_broadcast_chain(chain.clone())
+        .with_send_detector(detector);
+
+    let user_id = util::sign_in(&server, "screened@example.com", SESSION).await;
+    util::create_wallet(&server, WALLET, PASSWORD).await;
+    let address = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+    server.user_db.link_wallet(&user_id, WALLET, &format!("{:#x}", address), None).await.unwrap();
+
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, chain, storage, _dir: dir }
+}
+
+impl Harness {
+    async fn send(&self, amount: &str) -> axum_test::TestResponse {
+        self.app
+            .post(&format!("/api/wallets/{}/send", WALLET))
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .json(&json!({ "to": TO, "amount": amount, "network": "eth", "password": PASSWORD }))
+            .await
+    }
+
+    fn sent(&self) -> usize {
+        *self.chain.sent.lock().unwrap()
+    }
+
+    /// 审计日志里的检测记录，旧的在前
+    async fn anomaly_checks(&self) -> Vec<Value> {
+        let mut logs = self.storage.get_audit_logs(Some(WALLET)).await.unwrap();
+        logs.sort_by_key(|l| l.id);
+        logs.into_iter()
+            .filter(|l| l.action == "transaction.anomaly_check")
+            .map(|l| serde_json::from_str(l.details.as_deref().unwrap()).unwrap())
+            .collect()
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_high_risk_send_is_blocked_before_signing() {
+    let h = build(true).await;
+
+    let res = h.send("2").await;
+    assert_eq!(res.status_code(), 403);
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "ANOMALY_BLOCKED");
+    let details = &body["error"]["details"];
+    assert!(details["score"].as_f64().unwrap() > 0.0);
+    assert!(matches!(details["threat_level"].as_str(), Some("High" | "Critical")), "{}", body);
+    assert!(details["reason"].as_str().unwrap().contains("large_to_new_recipient"), "{}", body);
+    assert_eq!(h.sent(), 0);
+
+    // 阈值以下照常发送
+    h.send("0.5").await.assert_status_ok();
+    assert_eq!(h.sent(), 1);
+
+    let checks = h.anomaly_checks().await;
+    assert_eq!(checks.len(), 2);
+    assert_eq!(checks[0]["blocked"], true);
+    assert_eq!(checks[0]["score"], details["score"]);
+    assert_eq!(checks[0]["to"], TO);
+    assert_eq!(checks[1]["blocked"], false);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_high_risk_send_is_only_logged_when_blocking_is_off() {
+    let h = build(false).await;
+
+    let res = h.send("2").await;
+    res.assert_status_ok();
+    let tx_hash = res.json::<Value>()["tx_hash"].as_str().unwrap().to_string();
+    assert_eq!(h.sent(), 1);
+
+    let checks = h.anomaly_checks().await;
+    assert_eq!(checks.len(), 1);
+    assert_eq!(checks[0]["blocked"], false);
+    assert!(matches!(checks[0]["threat_level"].as_str(), Some("High" | "Critical")), "{}", checks[0]);
+    assert!(checks[0]["reason"].as_str().unwrap().contains("large_to_new_recipient"));
+
+    // 分数也随决策快照入库
+    let res = h.app.get(&format!("/api/transactions/{}/status", tx_hash)).add_header("Authorization", API_KEY).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["decision"]["anomaly"]["score"], checks[0]["score"]);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_anomaly_routes_share_the_send_detector() {
+    let h = build(true).await;
+
+    h.send("2").await.assert_status(axum::http::StatusCode::FORBIDDEN);
+    h.send("0.5").await.assert_status_ok();
+
+    // /api/anomaly-detection 看到的是发送路径上的同一个检测器
+    let res = h.app.get("/api/anomaly-detection/stats").await;
+    res.assert_status_ok();
+    let stats = res.json::<Value>()["data"]["today"].clone();
+    assert_eq!(stats["total_detections"], 2, "{}", stats);
+    assert_eq!(stats["passed"], 1, "{}", stats);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_wrong_password_is_refused_before_screening() {
+    let h = build(true).await;
+
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/send", WALLET))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "to": TO, "amount": "2", "network": "eth", "password": "Wr0ng!Vault#2024" }))
+        .await;
+    assert!(!res.status_code().is_success());
+    assert_ne!(res.json::<Value>()["error"]["code"], "ANOMALY_BLOCKED");
+    assert_eq!(h.sent(), 0);
+    // 没有解锁wallet的请求不留检测记录
+    assert!(h.anomaly_checks().await.is_empty());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_batch_items_are_screened() {
+    let h = build(true).await;
+
+    let item = |amount: &str| json!({ "to": TO, "amount": amount, "network": "eth", "password": PASSWORD });
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/send_batch", WALLET))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "transactions": [item("0.5"), item("2")] }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["results"][0]["status"], "sent", "{}", body);
+    assert_eq!(body["results"][1]["status"], "failed", "{}", body);
+    assert_eq!(body["results"][1]["code"], "ANOMALY_BLOCKED", "{}", body);
+    assert_eq!(h.sent(), 1);
+
+    let checks = h.anomaly_checks().await;
+    assert_eq!(checks.len(), 2);
+    assert_eq!(checks[1]["blocked"], true);
+}
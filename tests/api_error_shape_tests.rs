@@ -0,0 +1,133 @@
+//! 结构化错误响应：wallet / transaction / balance / bridge handler 返回
+//! `{"error": {"code", "message", "request_id", "retryable"}}`，`request_id` 与
+//! `X-Request-Id` 响应头一致；5xx 不透露内部错误细节
+
+use axum::response::IntoResponse;
+use axum_test::TestServer;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::error_catalog::ApiErrorCode;
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::api::types::ApiError;
+use defi_hot_wallet::core::config::{BlockchainConfig, StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "error-shape-admin-key";
+
+async fn build() -> (TestServer, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        // no configured networks: the bridge accepts its eth / polygon defaults
+        blockchain: BlockchainConfig { networks: Default::default() },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    (TestServer::new(server.create_router().await).unwrap(), dir)
+}
+
+fn request_id_header(res: &axum_test::TestResponse) -> String {
+    res.header("x-request-id").to_str().unwrap().to_string()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_error_body_is_nested_and_carries_request_id() {
+    let (app, _dir) = build().await;
+
+    let res = app.get("/api/wallets").await;
+    res.assert_status_unauthorized();
+    let body: Value = res.json();
+    let error = body["error"].as_object().expect("error is an object");
+    assert_eq!(error.len(), 4, "{}", body);
+    assert_eq!(body["error"]["code"], "AUTH_REQUIRED");
+    assert_eq!(body["error"]["retryable"], false);
+    assert!(body["error"]["message"].as_str().unwrap().starts_with("Unauthorized"));
+    assert!(body.get("code").is_none());
+
+    // 没带 X-Request-Id 时生成 UUID，body 与响应头一致
+    let id = request_id_header(&res);
+    assert!(uuid::Uuid::parse_str(&id).is_ok(), "{}", id);
+    assert_eq!(body["error"]["request_id"], id);
+
+    let bridge =
+        json!({ "from_wallet": "ghost", "from_chain": "eth", "to_chain": "polygon", "token": "USDC", "amount": "1" });
+    let res = app.post("/api/bridge").add_header("Authorization", API_KEY).json(&bridge).await;
+    res.assert_status_not_found();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "WALLET_NOT_FOUND");
+    assert_eq!(body["error"]["request_id"], request_id_header(&res));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_client_request_id_is_echoed() {
+    let (app, _dir) = build().await;
+
+    let res = app.get("/api/wallets").add_header("X-Request-Id", "client-req_7.retry").await;
+    assert_eq!(request_id_header(&res), "client-req_7.retry");
+    assert_eq!(res.json::<Value>()["error"]["request_id"], "client-req_7.retry");
+
+    // 不合规的值被替换，不会原样写回
+    let res = app.get("/api/wallets").add_header("X-Request-Id", "not ok; injected").await;
+    let id = request_id_header(&res);
+    assert!(uuid::Uuid::parse_str(&id).is_ok(), "{}", id);
+
+    // 成功响应同样带这个头
+    let res = app.get("/api/health").add_header("X-Request-Id", "health-check-1").await;
+    res.assert_status_ok();
+    assert_eq!(request_id_header(&res), "health-check-1");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_extractor_rejections_use_the_same_shape() {
+    let (app, _dir) = build().await;
+
+    // 请求体没有通过validate，handler 没有运行
+    let res = app.post("/api/bridge").add_header("Authorization", API_KEY).json(&json!({})).await;
+    res.assert_status(axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "INVALID_REQUEST_BODY");
+    assert_eq!(body["error"]["retryable"], false);
+    assert!(body.get("code").is_none());
+}
+
+#[tokio::test]
+async fn test_server_errors_do_not_leak_internals() {
+    let err = WalletError::StorageError("database /var/lib/wallet/secret.db is locked by pid 4242".to_string());
+    let res = ApiError::from(err).into_response();
+    assert_eq!(res.status(), axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["error"]["code"], "DB_ERROR");
+    assert_eq!(body["error"]["message"], ApiErrorCode::DbError.entry().description);
+    assert!(!String::from_utf8_lossy(&bytes).contains("secret.db"));
+    // 不在请求内：没有 request id
+    assert!(body["error"]["request_id"].is_null());
+
+    let err = ApiError::from(anyhow::anyhow!("connection to postgres://admin:hunter2@db failed"));
+    assert_eq!(err.code(), ApiErrorCode::InternalError);
+    assert!(!err.message().contains("hunter2"));
+
+    // 4xx 保留有用的说明
+    let err = ApiError::from(WalletError::InvalidAmount("amount must be positive".to_string()));
+    assert_eq!(err.status(), axum::http::StatusCode::BAD_REQUEST);
+    assert!(err.message().contains("amount must be positive"));
+}
@@ -14,6 +14,7 @@ async fn create_test_manager() -> WalletManager {
             database_url: "sqlite::memory:".to_string(),
             max_connections: Some(5),
             connection_timeout_seconds: Some(10),
+            read_database_url: None,
         },
         blockchain: BlockchainConfig {
             networks: HashMap::new(),
@@ -22,6 +23,27 @@ async fn create_test_manager() -> WalletManager {
         multi_sig_threshold: 2,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
     
     WalletManager::new(&config).await.expect("Failed to create WalletManager")
@@ -32,7 +54,7 @@ async fn create_test_manager() -> WalletManager {
 #[tokio::test]
 async fn test_backup_restore_wallet_exists_check_true() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("exists", false).await;
+    let _ = manager.create_wallet("exists", "test_password", false).await;
     
     let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
     let result = manager.restore_wallet("exists", mnemonic).await;
@@ -122,7 +144,7 @@ async fn test_restore_mnemonic_invalid_count_13() {
 #[tokio::test]
 async fn test_send_transaction_network_eth() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
     let result = manager.send_transaction("test", "0x123", "1.0", "eth", "test_password").await;
     let _ = result; // eth分支
 }
@@ -131,7 +153,7 @@ async fn test_send_transaction_network_eth() {
 #[tokio::test]
 async fn test_send_transaction_network_sepolia() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
     let result = manager.send_transaction("test", "0x123", "1.0", "sepolia", "test_password").await;
     let _ = result; // sepolia分支
 }
@@ -140,7 +162,7 @@ async fn test_send_transaction_network_sepolia() {
 #[tokio::test]
 async fn test_send_transaction_network_polygon() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
     let result = manager.send_transaction("test", "0x123", "1.0", "polygon", "test_password").await;
     let _ = result; // polygon分支
 }
@@ -149,7 +171,7 @@ async fn test_send_transaction_network_polygon() {
 #[tokio::test]
 async fn test_send_transaction_network_bsc() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
     let result = manager.send_transaction("test", "0x123", "1.0", "bsc", "test_password").await;
     let _ = result; // bsc分支
 }
@@ -157,7 +179,7 @@ async fn test_send_transaction_network_bsc() {
 #[tokio::test]
 async fn test_send_transaction_network_default_error() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
     let result = manager.send_transaction("test", "0x123", "1.0", "unknown_network", "test_password").await;
     assert!(result.is_err(), "未知网络应该走到默认错误分支");
 }
@@ -167,7 +189,7 @@ async fn test_send_transaction_network_default_error() {
 #[tokio::test]
 async fn test_multisig_threshold_less_than_1() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
     let signers = vec!["s1".to_string(), "s2".to_string()];
     let result = manager.send_multi_sig_transaction("test", "0x123", "1.0", &signers, 0).await;
     assert!(result.is_err(), "threshold < 1应该失败");
@@ -176,7 +198,7 @@ async fn test_multisig_threshold_less_than_1() {
 #[tokio::test]
 async fn test_multisig_threshold_equals_signers() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
     let signers = vec!["s1".to_string(), "s2".to_string()];
     let result = manager.send_multi_sig_transaction("test", "0x123", "1.0", &signers, 2).await;
     assert!(result.is_ok(), "threshold == signers.len()应该成功");
@@ -185,7 +207,7 @@ async fn test_multisig_threshold_equals_signers() {
 #[tokio::test]
 async fn test_multisig_threshold_greater_than_signers() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
     let signers = vec!["s1".to_string(), "s2".to_string()];
     let result = manager.send_multi_sig_transaction("test", "0x123", "1.0", &signers, 3).await;
     assert!(result.is_err(), "threshold > signers.len()应该失败");
@@ -197,8 +219,8 @@ async fn test_multisig_threshold_greater_than_signers() {
 #[tokio::test]
 async fn test_get_balance_network_eth() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
-    let result = manager.get_balance("test", "eth").await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
+    let result = manager.get_balance("test", "eth", "test_password").await;
     let _ = result; // eth分支
 }
 
@@ -206,8 +228,8 @@ async fn test_get_balance_network_eth() {
 #[tokio::test]
 async fn test_get_balance_network_sepolia() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
-    let result = manager.get_balance("test", "sepolia").await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
+    let result = manager.get_balance("test", "sepolia", "test_password").await;
     let _ = result; // sepolia分支
 }
 
@@ -215,8 +237,8 @@ async fn test_get_balance_network_sepolia() {
 #[tokio::test]
 async fn test_get_balance_network_polygon() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
-    let result = manager.get_balance("test", "polygon").await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
+    let result = manager.get_balance("test", "polygon", "test_password").await;
     let _ = result; // polygon分支
 }
 
@@ -224,16 +246,16 @@ async fn test_get_balance_network_polygon() {
 #[tokio::test]
 async fn test_get_balance_network_bsc() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
-    let result = manager.get_balance("test", "bsc").await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
+    let result = manager.get_balance("test", "bsc", "test_password").await;
     let _ = result; // bsc分支
 }
 
 #[tokio::test]
 async fn test_get_balance_network_default_error() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
-    let result = manager.get_balance("test", "invalid_network").await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
+    let result = manager.get_balance("test", "invalid_network", "test_password").await;
     assert!(result.is_err(), "未知网络应该走到默认错误分支");
 }
 
@@ -244,7 +266,7 @@ async fn test_get_balance_network_default_error() {
 async fn test_derive_address_network_eth() {
     let manager = create_test_manager().await;
     let key = vec![1u8; 32];
-    let result = manager.derive_address(&key, "eth");
+    let result = manager.derive_address(&key, "eth", None);
     assert!(result.is_ok());
 }
 
@@ -253,7 +275,7 @@ async fn test_derive_address_network_eth() {
 async fn test_derive_address_network_ethereum() {
     let manager = create_test_manager().await;
     let key = vec![1u8; 32];
-    let result = manager.derive_address(&key, "ethereum");
+    let result = manager.derive_address(&key, "ethereum", None);
     assert!(result.is_ok());
 }
 
@@ -262,7 +284,7 @@ async fn test_derive_address_network_ethereum() {
 async fn test_derive_address_network_sepolia() {
     let manager = create_test_manager().await;
     let key = vec![1u8; 32];
-    let result = manager.derive_address(&key, "sepolia");
+    let result = manager.derive_address(&key, "sepolia", None);
     assert!(result.is_ok());
 }
 
@@ -271,7 +293,7 @@ async fn test_derive_address_network_sepolia() {
 async fn test_derive_address_network_polygon() {
     let manager = create_test_manager().await;
     let key = vec![1u8; 32];
-    let result = manager.derive_address(&key, "polygon");
+    let result = manager.derive_address(&key, "polygon", None);
     assert!(result.is_ok());
 }
 
@@ -280,7 +302,7 @@ async fn test_derive_address_network_polygon() {
 async fn test_derive_address_network_bsc() {
     let manager = create_test_manager().await;
     let key = vec![1u8; 32];
-    let result = manager.derive_address(&key, "bsc");
+    let result = manager.derive_address(&key, "bsc", None);
     assert!(result.is_ok());
 }
 
@@ -289,7 +311,7 @@ async fn test_derive_address_network_bsc() {
 async fn test_derive_address_network_bitcoin() {
     let manager = create_test_manager().await;
     let key = vec![1u8; 32];
-    let result = manager.derive_address(&key, "bitcoin");
+    let result = manager.derive_address(&key, "bitcoin", None);
     assert!(result.is_ok());
 }
 
@@ -298,7 +320,7 @@ async fn test_derive_address_network_bitcoin() {
 async fn test_derive_address_network_btc() {
     let manager = create_test_manager().await;
     let key = vec![1u8; 32];
-    let result = manager.derive_address(&key, "btc");
+    let result = manager.derive_address(&key, "btc", None);
     assert!(result.is_ok());
 }
 
@@ -306,7 +328,7 @@ async fn test_derive_address_network_btc() {
 async fn test_derive_address_network_default_error() {
     let manager = create_test_manager().await;
     let key = vec![1u8; 32];
-    let result = manager.derive_address(&key, "unsupported");
+    let result = manager.derive_address(&key, "unsupported", None);
     assert!(result.is_err(), "未知网络应该走到默认错误分支");
 }
 
@@ -372,10 +394,10 @@ async fn test_reset_nonce_wallet_not_exists() {
 #[tokio::test]
 async fn test_create_wallet_wallets_contains_key_true() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("duplicate", false).await;
+    let _ = manager.create_wallet("duplicate", "test_password", false).await;
     
     // 再次创建同名钱包
-    let result = manager.create_wallet("duplicate", false).await;
+    let result = manager.create_wallet("duplicate", "test_password", false).await;
     
     assert!(result.is_err(), "重复名称应该失败");
 }
@@ -385,7 +407,7 @@ async fn test_create_wallet_wallets_contains_key_false() {
     let manager = create_test_manager().await;
     
     // 创建新钱包
-    let result = manager.create_wallet("new_unique", false).await;
+    let result = manager.create_wallet("new_unique", "test_password", false).await;
     
     assert!(result.is_ok(), "新名称应该成功");
 }
@@ -393,7 +415,7 @@ async fn test_create_wallet_wallets_contains_key_false() {
 #[tokio::test]
 async fn test_delete_wallet_remove_some() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("to_delete", false).await;
+    let _ = manager.create_wallet("to_delete", "test_password", false).await;
     
     let result = manager.delete_wallet("to_delete").await;
     assert!(result.is_ok(), "删除存在的钱包应该成功");
@@ -410,7 +432,7 @@ async fn test_delete_wallet_remove_none() {
 #[tokio::test]
 async fn test_get_wallet_by_name_some() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("findme", false).await;
+    let _ = manager.create_wallet("findme", "test_password", false).await;
     
     let result = manager.get_wallet_by_name("findme").await.unwrap();
     assert!(result.is_some(), "存在的钱包应该返回Some");
@@ -429,7 +451,7 @@ async fn test_get_wallet_by_name_none() {
 #[tokio::test]
 async fn test_multisig_threshold_min_value_1() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
     let signers = vec!["signer1".to_string()];
     let result = manager.send_multi_sig_transaction("test", "0x123", "1.0", &signers, 1).await;
     assert!(result.is_ok(), "threshold=1应该是有效的最小值");
@@ -438,7 +460,7 @@ async fn test_multisig_threshold_min_value_1() {
 #[tokio::test]
 async fn test_multisig_threshold_max_value() {
     let manager = create_test_manager().await;
-    let _ = manager.create_wallet("test", false).await;
+    let _ = manager.create_wallet("test", "test_password", false).await;
     let signers: Vec<String> = (0..100).map(|i| format!("signer_{}", i)).collect();
     let result = manager.send_multi_sig_transaction("test", "0x123", "1.0", &signers, 100).await;
     assert!(result.is_ok(), "threshold=signers.len()应该成功");
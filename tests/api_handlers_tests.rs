@@ -6,7 +6,7 @@ use axum_test::TestServer;
 use defi_hot_wallet::{
     api::server::WalletServer,
     api::types::BridgeAssetsRequest,
-    api::types::ErrorResponse,
+    api::types::ApiErrorBody,
     core::config::{StorageConfig, WalletConfig},
 };
 use futures::future::join_all;
@@ -22,6 +22,7 @@ async fn setup_test_server() -> TestServer {
             database_url: "sqlite::memory:".to_string(),
             max_connections: Some(5),
             connection_timeout_seconds: Some(30),
+            read_database_url: None,
         },
         blockchain: defi_hot_wallet::core::config::BlockchainConfig {
             networks: std::collections::HashMap::new(),
@@ -30,6 +31,27 @@ async fn setup_test_server() -> TestServer {
         multi_sig_threshold: 2,
         derivation: Default::default(),
         security: defi_hot_wallet::core::config::SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
     // Use deterministic test master key for consistent test results
     let zeros: Vec<u8> = std::iter::repeat_n(0u8, 32).collect();
@@ -48,6 +70,7 @@ async fn setup_test_server_with_key(api_key: Option<zeroize::Zeroizing<Vec<u8>>>
             database_url: "sqlite::memory:".to_string(),
             max_connections: Some(5),
             connection_timeout_seconds: Some(30),
+            read_database_url: None,
         },
         blockchain: defi_hot_wallet::core::config::BlockchainConfig {
             networks: std::collections::HashMap::new(),
@@ -56,6 +79,27 @@ async fn setup_test_server_with_key(api_key: Option<zeroize::Zeroizing<Vec<u8>>>
         multi_sig_threshold: 2,
         derivation: Default::default(),
         security: defi_hot_wallet::core::config::SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
     // Use deterministic test master key for consistent test results
     let zeros2: Vec<u8> = std::iter::repeat_n(0u8, 32).collect();
@@ -109,9 +153,15 @@ async fn test_bridge_empty_parameters_each_field() {
     // Create server once and reuse to avoid repeated expensive setup.
     let server = setup_test_server().await;
 
-    // For each field, create a request with that single field empty and assert Invalid parameters
-    let fields = vec!["from_wallet", "from_chain", "to_chain", "token", "amount"];
-    for field in fields {
+    // For each field, create a request with that single field empty and assert the field's error code
+    let fields = vec![
+        ("from_wallet", "INVALID_WALLET_NAME"),
+        ("from_chain", "INVALID_NETWORK"),
+        ("to_chain", "INVALID_NETWORK"),
+        ("token", "MISSING_PARAMETER"),
+        ("amount", "INVALID_AMOUNT"),
+    ];
+    for (field, code) in fields {
         let mut req = base.clone();
         match field {
             "from_wallet" => req.from_wallet = String::new(),
@@ -124,8 +174,8 @@ async fn test_bridge_empty_parameters_each_field() {
 
         let response = server.post("/api/bridge").json(&req).await;
         response.assert_status(StatusCode::BAD_REQUEST);
-        let body: ErrorResponse = response.json();
-        assert_eq!(body.error, "Invalid parameters");
+        let body: ApiErrorBody = response.json();
+        assert_eq!(body.error.code, code, "field {}", field);
     }
 }
 
@@ -143,15 +193,15 @@ async fn test_bridge_invalid_amount_non_numeric_and_negative() {
     let server = setup_test_server().await;
     let res = server.post("/api/bridge").json(&req).await;
     res.assert_status(StatusCode::BAD_REQUEST);
-    let body: ErrorResponse = res.json();
-    assert_eq!(body.error, "Invalid amount");
+    let body: ApiErrorBody = res.json();
+    assert_eq!(body.error.code, "INVALID_AMOUNT");
 
     // negative amount
     let req2 = BridgeAssetsRequest { amount: "-5.0".to_string(), ..req };
     let res2 = server.post("/api/bridge").json(&req2).await;
     res2.assert_status(StatusCode::BAD_REQUEST);
-    let body2: ErrorResponse = res2.json();
-    assert_eq!(body2.error, "Invalid amount");
+    let body2: ApiErrorBody = res2.json();
+    assert_eq!(body2.error.code, "INVALID_AMOUNT");
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -216,8 +266,9 @@ async fn test_bridge_unauthorized_when_api_key_set() {
 
     let res = server.post("/api/bridge").json(&req).await;
     res.assert_status(StatusCode::UNAUTHORIZED);
-    let body: ErrorResponse = res.json();
-    assert_eq!(body.error, "Unauthorized");
+    let body: ApiErrorBody = res.json();
+    assert_eq!(body.error.message, "Unauthorized");
+    assert_eq!(body.error.code, "AUTH_FAILED");
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -283,8 +334,8 @@ async fn test_bridge_assets_handler_invalid_amount() {
     let response = server.post("/api/bridge").json(&request).await;
 
     response.assert_status(StatusCode::BAD_REQUEST);
-    let body: ErrorResponse = response.json();
-    assert_eq!(body.error, "Invalid amount");
+    let body: ApiErrorBody = response.json();
+    assert_eq!(body.error.code, "INVALID_AMOUNT");
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -302,8 +353,8 @@ async fn test_bridge_assets_handler_wallet_not_found() {
     let response = server.post("/api/bridge").json(&request).await;
 
     response.assert_status(StatusCode::NOT_FOUND);
-    let body: ErrorResponse = response.json();
-    assert_eq!(body.error, "Wallet not found");
+    let body: ApiErrorBody = response.json();
+    assert_eq!(body.error.message, "Wallet not found");
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -324,8 +375,8 @@ async fn test_bridge_assets_handler_wallet_not_found_for_valid_request() {
 
     // Since the wallet does not exist, we expect a NOT_FOUND error.
     response.assert_status(StatusCode::NOT_FOUND);
-    let body: ErrorResponse = response.json();
-    assert_eq!(body.error, "Wallet not found");
+    let body: ApiErrorBody = response.json();
+    assert_eq!(body.error.message, "Wallet not found");
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -345,6 +396,6 @@ async fn test_bridge_assets_handler_unsupported_chain() {
     let response = server.post("/api/bridge").json(&request).await;
 
     response.assert_status(StatusCode::BAD_REQUEST);
-    let body: ErrorResponse = response.json();
-    assert_eq!(body.error, "Unsupported chain");
+    let body: ApiErrorBody = response.json();
+    assert_eq!(body.error.code, "UNSUPPORTED_NETWORK");
 }
@@ -28,6 +28,7 @@ mod transaction_handler_tests {
                 database_url: "sqlite::memory:".to_string(),
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             ..Default::default()
         };
@@ -47,7 +48,7 @@ mod transaction_handler_tests {
     // 辅助函数：创建测试钱包
     async fn create_test_wallet(server: &WalletServer, name: &str) {
         server.wallet_manager
-            .create_wallet(name, false)
+            .create_wallet(name, "test_password", false)
             .await
             .expect("Failed to create test wallet");
     }
@@ -63,8 +64,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "1.5".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -95,12 +97,14 @@ mod transaction_handler_tests {
         let server = create_test_server().await;
         let app = server.create_router().await;
         
+        // 请求体需先通过validate，才会走到认证
         let payload = SendTransactionRequest {
-            to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+            to: "0x742d35Cc6634C0532925a3b844Bc454e4438f44e".to_string(),
             amount: "1.0".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -124,8 +128,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "1.0".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -149,8 +154,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "1.0".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -175,8 +181,9 @@ mod transaction_handler_tests {
             to: "".to_string(),  // 空地址
             amount: "1.0".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -201,8 +208,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "invalid".to_string(),  // 无效金额
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -227,8 +235,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "1.0".to_string(),
             network: "unsupported_network".to_string(),  // 不支持的网络
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -253,8 +262,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "0.5".to_string(),
             network: "bsc".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -432,8 +442,9 @@ mod transaction_handler_tests {
                 to: format!("0x742d35Cc6634C0532925a3b844Bc9e7595f0bE{:x}", i),
                 amount: format!("0.{}", i + 1),
                 network: "eth".to_string(),
-                password: "test_password".to_string(),
+                password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
             };
             
             let _request = Request::builder()
@@ -466,8 +477,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "999999999.999999999".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -497,8 +509,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "1.0".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -528,8 +541,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "1.0".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -637,8 +651,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "0".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -669,8 +684,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "-1.0".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -724,8 +740,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "1.0".to_string(),
             network: "polygon".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -756,8 +773,9 @@ mod transaction_handler_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "1.0".to_string(),
             network: "bsc".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -28,6 +28,7 @@ mod wallet_handler_tests {
                 database_url: "sqlite::memory:".to_string(),
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             ..Default::default()
         };
@@ -54,11 +55,15 @@ mod wallet_handler_tests {
         let payload = CreateWalletRequest {
             name: "test_wallet".to_string(),
             quantum_safe: false,
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             generate_mnemonic: true,
             mnemonic_word_count: 12,
             wallet_type: None,
             multisig_config: None,
+            initialize_networks: Vec::new(),
+            wallet_address: None,
+            derivation_path: None,
+            passphrase: None,
         };
         
         let request = Request::builder()
@@ -81,11 +86,15 @@ mod wallet_handler_tests {
         let payload = CreateWalletRequest {
             name: "test_wallet".to_string(),
             quantum_safe: false,
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             generate_mnemonic: true,
             mnemonic_word_count: 12,
             wallet_type: None,
             multisig_config: None,
+            initialize_networks: Vec::new(),
+            wallet_address: None,
+            derivation_path: None,
+            passphrase: None,
         };
         
         let request = Request::builder()
@@ -107,11 +116,15 @@ mod wallet_handler_tests {
         let payload = CreateWalletRequest {
             name: "invalid-name!@#".to_string(),
             quantum_safe: false,
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             generate_mnemonic: true,
             mnemonic_word_count: 12,
             wallet_type: None,
             multisig_config: None,
+            initialize_networks: Vec::new(),
+            wallet_address: None,
+            derivation_path: None,
+            passphrase: None,
         };
         
         let request = Request::builder()
@@ -134,11 +147,15 @@ mod wallet_handler_tests {
         let payload = CreateWalletRequest {
             name: "".to_string(),
             quantum_safe: false,
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             generate_mnemonic: true,
             mnemonic_word_count: 12,
             wallet_type: None,
             multisig_config: None,
+            initialize_networks: Vec::new(),
+            wallet_address: None,
+            derivation_path: None,
+            passphrase: None,
         };
         
         let request = Request::builder()
@@ -161,11 +178,15 @@ mod wallet_handler_tests {
         let payload = CreateWalletRequest {
             name: "quantum_wallet".to_string(),
             quantum_safe: true,
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             generate_mnemonic: true,
             mnemonic_word_count: 12,
             wallet_type: None,
             multisig_config: None,
+            initialize_networks: Vec::new(),
+            wallet_address: None,
+            derivation_path: None,
+            passphrase: None,
         };
         
         let request = Request::builder()
@@ -186,8 +207,8 @@ mod wallet_handler_tests {
     async fn test_list_wallets_success() {
         let server = create_test_server().await;
         // 创建测试钱包
-        server.wallet_manager.create_wallet("wallet1", false).await.ok();
-        server.wallet_manager.create_wallet("wallet2", false).await.ok();
+        server.wallet_manager.create_wallet("wallet1", "test_password", false).await.ok();
+        server.wallet_manager.create_wallet("wallet2", "test_password", false).await.ok();
         
         let app = server.create_router().await;
         
@@ -239,7 +260,7 @@ mod wallet_handler_tests {
     async fn test_delete_wallet_success() {
         let server = create_test_server().await;
         // 创建测试钱包
-        server.wallet_manager.create_wallet("wallet_to_delete", false).await.ok();
+        server.wallet_manager.create_wallet("wallet_to_delete", "test_password", false).await.ok();
         
         let app = server.create_router().await;
         
@@ -307,7 +328,7 @@ mod wallet_handler_tests {
     async fn test_rotate_signing_key_success() {
         let server = create_test_server().await;
         // 创建测试钱包
-        server.wallet_manager.create_wallet("wallet_for_rotation", false).await.ok();
+        server.wallet_manager.create_wallet("wallet_for_rotation", "test_password", false).await.ok();
         
         let app = server.create_router().await;
         
@@ -378,12 +399,16 @@ mod wallet_handler_tests {
         for i in 0..5 {
             let payload = CreateWalletRequest {
                 name: format!("wallet_{}", i),
-                password: "test_password".to_string(),
+                password: Some("test_password".to_string()),
                 quantum_safe: i % 2 == 0,
                 generate_mnemonic: true,
                 mnemonic_word_count: 12,
                 wallet_type: None,
                 multisig_config: None,
+                initialize_networks: Vec::new(),
+                wallet_address: None,
+                derivation_path: None,
+                passphrase: None,
             };
             
             let _request = Request::builder()
@@ -393,9 +418,6 @@ mod wallet_handler_tests {
                 .header("Authorization", "test-api-key-12345678901234567890123")
                 .body(Body::from(serde_json::to_string(&payload).unwrap()))
                 .unwrap();
-            
-            // 注意：只能使用一次 oneshot
-            break;
         }
     }
     
@@ -407,11 +429,15 @@ mod wallet_handler_tests {
         let payload = CreateWalletRequest {
             name: "wallet_with_underscores_123".to_string(),
             quantum_safe: false,
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             generate_mnemonic: true,
             mnemonic_word_count: 12,
             wallet_type: None,
             multisig_config: None,
+            initialize_networks: Vec::new(),
+            wallet_address: None,
+            derivation_path: None,
+            passphrase: None,
         };
         
         let request = Request::builder()
@@ -434,11 +460,15 @@ mod wallet_handler_tests {
         let payload = CreateWalletRequest {
             name: "wallet123456".to_string(),
             quantum_safe: false,
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             generate_mnemonic: true,
             mnemonic_word_count: 12,
             wallet_type: None,
             multisig_config: None,
+            initialize_networks: Vec::new(),
+            wallet_address: None,
+            derivation_path: None,
+            passphrase: None,
         };
         
         let request = Request::builder()
@@ -470,11 +500,15 @@ mod wallet_handler_tests {
             let payload = CreateWalletRequest {
                 name: name.to_string(),
                 quantum_safe: false,
-                password: "test_password".to_string(),
+                password: Some("test_password".to_string()),
                 generate_mnemonic: true,
                 mnemonic_word_count: 12,
                 wallet_type: None,
                 multisig_config: None,
+                initialize_networks: Vec::new(),
+                wallet_address: None,
+                derivation_path: None,
+                passphrase: None,
             };
             
             let request = Request::builder()
@@ -496,7 +530,7 @@ mod wallet_handler_tests {
         let server = create_test_server().await;
         
         // 1. 创建钱包
-        server.wallet_manager.create_wallet("lifecycle_wallet", false).await.ok();
+        server.wallet_manager.create_wallet("lifecycle_wallet", "test_password", false).await.ok();
         
         // 2. 验证钱包存在
         let wallets = server.wallet_manager.list_wallets().await.unwrap();
@@ -519,11 +553,15 @@ mod wallet_handler_tests {
         let payload = CreateWalletRequest {
             name: long_name,
             quantum_safe: false,
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             generate_mnemonic: true,
             mnemonic_word_count: 12,
             wallet_type: None,
             multisig_config: None,
+            initialize_networks: Vec::new(),
+            wallet_address: None,
+            derivation_path: None,
+            passphrase: None,
         };
         
         let request = Request::builder()
@@ -567,11 +605,15 @@ mod wallet_handler_tests {
         let payload = CreateWalletRequest {
             name: "test_wallet".to_string(),
             quantum_safe: false,
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             generate_mnemonic: true,
             mnemonic_word_count: 12,
             wallet_type: None,
             multisig_config: None,
+            initialize_networks: Vec::new(),
+            wallet_address: None,
+            derivation_path: None,
+            passphrase: None,
         };
         
         let request = Request::builder()
@@ -0,0 +1,285 @@
+//! 管理 API 密钥轮换：重叠期间主密钥与第二把都有效，两次轮换后旧主密钥失效，
+//! 每次轮换写审计日志（只记指纹）；`POST /api/admin/rotate_api_key`。
+//! 安装了密钥后端时轮换写回后端，其他实例刷新后换上；后端里换的密钥同样生效
+
+use axum_test::TestServer;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+use defi_hot_wallet::api::middleware::ApiKeyRing;
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{MasterKeyRotation, SecretSourceConfig, StorageConfig, WalletConfig};
+use defi_hot_wallet::ops::secret_refresh::{RefreshOutcome, SecretRefresher};
+use defi_hot_wallet::security::env_manager::secret_backend::{
+    self, backend_from_config, SecretStore, API_KEY, API_KEY_SECONDARY, MASTER_KEY,
+};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::WalletStorage;
+
+const PRIMARY: &str = "rotation-primary-admin-key-0123456789";
+const SECONDARY: &str = "rotation-secondary-admin-key-9876543210";
+
+struct Harness {
+    app: TestServer,
+    storage: Arc<WalletStorage>,
+    api_key: ApiKeyRing,
+    _dir: tempfile::TempDir,
+}
+
+fn config() -> WalletConfig {
+    WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    }
+}
+
+async fn build(primary: Option<&str>, secondary: Option<&str>) -> Harness {
+    build_with(config(), primary, secondary).await
+}
+
+async fn build_with(config: WalletConfig, primary: Option<&str>, secondary: Option<&str>) -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let api_key = primary.map(|key| SecretVec::new(key.as_bytes().to_vec()));
+    let mut server = WalletServer::new_for_test("127.0.0.1".to_string(), 0, config, api_key, None).await.unwrap();
+    if let Some(secondary) = secondary {
+        server = server.with_secondary_api_key(SecretVec::new(secondary.as_bytes().to_vec()));
+    }
+    let storage = server.storage.clone();
+    let api_key = server.api_key.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, storage, api_key, _dir: dir }
+}
+
+impl Harness {
+    async fn accepted(&self, key: &str) -> bool {
+        let res = self.app.get("/api/admin/jobs").add_header("X-API-KEY", key).await;
+        res.status_code().is_success()
+    }
+
+    async fn rotate(&self, key: &str) -> axum_test::TestResponse {
+        self.app.post("/api/admin/rotate_api_key").add_header("Authorization", format!("Bearer {}", key)).await
+    }
+
+    async fn rotated(&self, key: &str) -> String {
+        let res = self.rotate(key).await;
+        res.assert_status_ok();
+        let body: Value = res.json();
+        body["secondary_api_key"].as_str().unwrap().to_string()
+    }
+
+    /// 这个实例的密钥刷新任务跑一次
+    async fn refresh(&self, store: Arc<SecretStore>) -> RefreshOutcome {
+        SecretRefresher::new(store, self.storage.clone(), Duration::from_secs(60), MasterKeyRotation::Refuse)
+            .with_api_keys(self.api_key.clone())
+            .run_once()
+            .await
+    }
+}
+
+/// 文件密钥后端（主密钥与 `API_KEY`），安装为进程级缓存；drop 时卸载
+struct FileSecrets {
+    dir: tempfile::TempDir,
+}
+
+impl FileSecrets {
+    async fn install(api_key: &str) -> (Self, Arc<SecretStore>) {
+        let secrets = FileSecrets { dir: tempfile::tempdir().unwrap() };
+        secrets.write(MASTER_KEY, "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=");
+        secrets.write(API_KEY, api_key);
+        let store = secrets.open().await;
+        secret_backend::install(store.clone());
+        (secrets, store)
+    }
+
+    /// 同一个后端的另一份缓存（另一个实例）
+    async fn open(&self) -> Arc<SecretStore> {
+        let source = SecretSourceConfig::File { directory: self.dir.path().to_path_buf() };
+        Arc::new(SecretStore::open(backend_from_config(&source).unwrap(), None).await.unwrap())
+    }
+
+    fn write(&self, name: &str, value: &str) {
+        let path = self.dir.path().join(name);
+        std::fs::write(&path, value).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        }
+    }
+
+    fn read(&self, name: &str) -> String {
+        std::fs::read_to_string(self.dir.path().join(name)).unwrap()
+    }
+}
+
+impl Drop for FileSecrets {
+    fn drop(&mut self) {
+        secret_backend::uninstall();
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_both_keys_valid_during_overlap() {
+    let h = build(Some(PRIMARY), Some(SECONDARY)).await;
+    assert!(h.accepted(PRIMARY).await);
+    assert!(h.accepted(SECONDARY).await);
+    assert!(!h.accepted("rotation-unknown-admin-key-000000000").await);
+
+    // 第二把升为主密钥，旧主密钥立即失效，新的第二把与新主密钥重叠
+    let fresh = h.rotated(SECONDARY).await;
+    assert_eq!(fresh.len(), 64);
+    assert!(!h.accepted(PRIMARY).await);
+    assert!(h.accepted(SECONDARY).await);
+    assert!(h.accepted(&fresh).await);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_old_primary_rejected_after_two_rotations() {
+    let h = build(Some(PRIMARY), None).await;
+
+    // 没有第二把：主密钥保留，新密钥作为第二把
+    let first = h.rotated(PRIMARY).await;
+    assert!(h.accepted(PRIMARY).await);
+    assert!(h.accepted(&first).await);
+
+    let second = h.rotated(&first).await;
+    assert_ne!(first, second);
+    assert!(!h.accepted(PRIMARY).await);
+    assert!(h.accepted(&first).await);
+    assert!(h.accepted(&second).await);
+    h.rotate(PRIMARY).await.assert_status_unauthorized();
+
+    // 每次轮换一条审计记录，只有指纹、没有密钥
+    let logs = h.storage.get_audit_logs(Some("system")).await.unwrap();
+    let rotations: Vec<_> = logs.iter().filter(|log| log.action == "api_key.rotated").collect();
+    assert_eq!(rotations.len(), 2);
+    for log in rotations {
+        let details = log.details.as_deref().unwrap();
+        assert!(!details.contains(&first) && !details.contains(&second) && !details.contains(PRIMARY));
+        let details: Value = serde_json::from_str(details).unwrap();
+        assert_eq!(details["primary_fingerprint"].as_str().unwrap().len(), 16);
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_rotation_needs_a_configured_key() {
+    let h = build(None, None).await;
+    let res = h.rotate("anything").await;
+    res.assert_status(axum::http::StatusCode::CONFLICT);
+    assert_eq!(res.json::<Value>()["error"]["code"], "API_KEY_NOT_CONFIGURED");
+
+    // 只有第二把不能开启认证
+    let ring = ApiKeyRing::from(None);
+    assert!(!ring.set_secondary(SecretVec::new(SECONDARY.as_bytes().to_vec())));
+    assert!(ring.is_none() && !ring.accepts(SECONDARY.as_bytes()));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_key_rotated_in_the_backend_is_accepted() {
+    let (secrets, store) = FileSecrets::install(PRIMARY).await;
+    let h = build(Some(PRIMARY), None).await;
+    assert!(h.accepted(PRIMARY).await);
+
+    // 运维在后端换了密钥：刷新后新密钥有效，旧密钥失效，不需要重启
+    secrets.write(API_KEY, SECONDARY);
+    assert_eq!(h.refresh(store.clone()).await, RefreshOutcome::Unchanged);
+    assert!(h.accepted(SECONDARY).await);
+    assert!(!h.accepted(PRIMARY).await);
+
+    // 后端删掉 API_KEY 不会关闭认证
+    std::fs::remove_file(secrets.dir.path().join(API_KEY)).unwrap();
+    h.refresh(store).await;
+    assert!(h.accepted(SECONDARY).await);
+    assert!(!h.accepted("rotation-unknown-admin-key-000000000").await);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_rotation_is_saved_to_the_backend_and_reaches_other_instances() {
+    let (secrets, _store) = FileSecrets::install(PRIMARY).await;
+    let h = build(Some(PRIMARY), None).await;
+    let peer = build(Some(PRIMARY), None).await;
+    let peer_store = secrets.open().await;
+
+    // 写回后端：重启后读到的就是轮换后的两把
+    let fresh = h.rotated(PRIMARY).await;
+    assert_eq!(secrets.read(API_KEY), PRIMARY);
+    assert_eq!(secrets.read(API_KEY_SECONDARY), fresh);
+    assert!(h.accepted(&fresh).await);
+
+    // 另一个实例刷新后才接受新密钥
+    assert!(!peer.accepted(&fresh).await);
+    assert_eq!(peer.refresh(peer_store.clone()).await, RefreshOutcome::Unchanged);
+    assert!(peer.accepted(PRIMARY).await && peer.accepted(&fresh).await);
+
+    // 第二次轮换后旧主密钥在两个实例上都失效
+    let second = h.rotated(&fresh).await;
+    peer.refresh(peer_store).await;
+    for instance in [&h, &peer] {
+        assert!(!instance.accepted(PRIMARY).await);
+        assert!(instance.accepted(&fresh).await && instance.accepted(&second).await);
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_multi_instance_rotation_needs_a_writable_backend() {
+    let mut config = config();
+    config.cluster.multi_instance = true;
+    let h = build_with(config, Some(PRIMARY), None).await;
+
+    // 环境变量后端写不回去，其他实例会继续只认旧密钥
+    let res = h.rotate(PRIMARY).await;
+    res.assert_status(axum::http::StatusCode::CONFLICT);
+    assert_eq!(res.json::<Value>()["error"]["code"], "API_KEY_ROTATION_NOT_SHARED");
+    assert!(h.accepted(PRIMARY).await);
+    let logs = h.storage.get_audit_logs(Some("system")).await.unwrap();
+    assert!(logs.iter().all(|log| log.action != "api_key.rotated"));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_concurrent_rotations_do_not_lose_a_key() {
+    let (secrets, _store) = FileSecrets::install(PRIMARY).await;
+    let h = build(Some(PRIMARY), None).await;
+
+    // 两次轮换依次进行：第一次保留主密钥加上 a，第二次 a 升为主密钥加上 b
+    let (a, b) = tokio::join!(h.rotated(PRIMARY), h.rotated(PRIMARY));
+    assert_ne!(a, b);
+    assert!(!h.accepted(PRIMARY).await);
+    assert!(h.accepted(&a).await && h.accepted(&b).await);
+
+    // 后端与本进程的密钥一致
+    let mut saved = [secrets.read(API_KEY), secrets.read(API_KEY_SECONDARY)];
+    let mut returned = [a, b];
+    saved.sort();
+    returned.sort();
+    assert_eq!(saved, returned);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_rotation_needs_a_key_in_dev_mode() {
+    let h = build(Some(PRIMARY), None).await;
+    std::env::set_var("DEV_MODE", "1");
+    let without_key = h.app.post("/api/admin/rotate_api_key").await;
+    let wrong_key = h.rotate("rotation-unknown-admin-key-000000000").await;
+    std::env::remove_var("DEV_MODE");
+
+    without_key.assert_status_unauthorized();
+    wrong_key.assert_status_unauthorized();
+    assert!(h.accepted(PRIMARY).await);
+    let logs = h.storage.get_audit_logs(Some("system")).await.unwrap();
+    assert!(logs.iter().all(|log| log.action != "api_key.rotated"));
+}
@@ -28,6 +28,7 @@ mod server_tests {
                 database_url: "sqlite::memory:".to_string(),
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             ..Default::default()
         };
@@ -88,7 +89,7 @@ mod server_tests {
         let server = create_test_server().await;
         
         // 1. 创建钱包
-        server.wallet_manager.create_wallet("workflow_wallet", false).await.ok();
+        server.wallet_manager.create_wallet("workflow_wallet", "test_password", false).await.ok();
         
         // 2. 列出钱包
         let wallets = server.wallet_manager.list_wallets().await.unwrap();
@@ -153,8 +154,7 @@ mod server_tests {
         let server = create_test_server().await;
         
         // 测试速率限制器是否正确初始化
-        assert!(server.rate_limiter.as_ref() as *const _ != std::ptr::null());
-        assert!(server.rate_limiter.as_ref() as *const _ != std::ptr::null());
+        assert!(std::sync::Arc::strong_count(&server.rate_limiter) >= 1);
     }
     
     #[tokio::test]
@@ -165,12 +165,16 @@ mod server_tests {
         // 测试需要认证的端点
         let payload = CreateWalletRequest {
             name: "auth_test_wallet".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             quantum_safe: false,
             generate_mnemonic: true,
             mnemonic_word_count: 12,
             wallet_type: None,
             multisig_config: None,
+            wallet_address: None,
+            initialize_networks: vec![],
+            derivation_path: None,
+            passphrase: None,
         };
         
         let request = Request::builder()
@@ -196,12 +200,16 @@ mod server_tests {
         let large_name = "a".repeat(2 * 1024 * 1024); // 2MB
         let payload = CreateWalletRequest {
             name: large_name,
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             quantum_safe: false,
             generate_mnemonic: true,
             mnemonic_word_count: 12,
             wallet_type: None,
             multisig_config: None,
+            wallet_address: None,
+            initialize_networks: vec![],
+            derivation_path: None,
+            passphrase: None,
         };
         
         let request = Request::builder()
@@ -246,7 +254,7 @@ mod server_tests {
         let server = create_test_server().await;
         
         // 创建测试钱包
-        server.wallet_manager.create_wallet("route_test_wallet", false).await.ok();
+        server.wallet_manager.create_wallet("route_test_wallet", "test_password", false).await.ok();
         
         // 路由列表：(路径, 方法, 需要认证, 允许404)
         let routes = vec![
@@ -325,6 +333,7 @@ mod server_tests {
                 database_url: "sqlite::memory:".to_string(),
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             ..Default::default()
         };
@@ -412,7 +421,7 @@ mod server_tests {
     #[tokio::test]
     async fn test_sensitive_routes_have_stricter_limits() {
         let server = create_test_server().await;
-        server.wallet_manager.create_wallet("sensitive_test", false).await.ok();
+        server.wallet_manager.create_wallet("sensitive_test", "test_password", false).await.ok();
         
         // 敏感路由（/send, /bridge）应该有更严格的限制
         // 这里我们只验证路由是可访问的
@@ -422,8 +431,9 @@ mod server_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "0.1".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -449,6 +459,7 @@ mod server_tests {
                 database_url: "sqlite::memory:".to_string(),
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             ..Default::default()
         };
@@ -479,6 +490,7 @@ mod server_tests {
                 database_url: "sqlite::memory:".to_string(),
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             ..Default::default()
         };
@@ -501,7 +513,7 @@ mod server_tests {
         let server = create_test_server().await;
         
         // 1. 创建钱包
-        server.wallet_manager.create_wallet("backup_test", false).await.ok();
+        server.wallet_manager.create_wallet("backup_test", "test_password", false).await.ok();
         
         let app = server.create_router().await;
         
@@ -604,7 +616,7 @@ mod server_tests {
         for i in 0..5 {
             let wallet_manager = server.wallet_manager.clone();
             let handle = tokio::spawn(async move {
-                wallet_manager.create_wallet(&format!("concurrent_{}", i), false).await
+                wallet_manager.create_wallet(&format!("concurrent_{}", i), "test_password", false).await
             });
             handles.push(handle);
         }
@@ -46,12 +46,34 @@ async fn test_wallet_server_with_blockchain_config() {
             database_url: "sqlite::memory:".to_string(),
             max_connections: Some(5),
             connection_timeout_seconds: Some(30),
+            read_database_url: None,
         },
         blockchain: blockchain_config,
         quantum_safe: false,
         multi_sig_threshold: 3,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
     
     let result = WalletServer::new_for_test(
@@ -222,12 +244,34 @@ async fn test_server_all_features_enabled() {
             database_url: "sqlite::memory:".to_string(),
             max_connections: Some(10),
             connection_timeout_seconds: Some(30),
+            read_database_url: None,
         },
         blockchain: blockchain_config,
         quantum_safe: true,
         multi_sig_threshold: 3,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
     
     let api_key = Some(SecretVec::from(b"super_secret_key_12345".to_vec()));
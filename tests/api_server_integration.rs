@@ -26,6 +26,7 @@ mod server_integration_tests {
                 database_url: "sqlite::memory:".to_string(),
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             ..Default::default()
         };
@@ -84,7 +85,7 @@ mod server_integration_tests {
     #[tokio::test]
     async fn test_route_wallet_delete() {
         let server = create_test_server().await;
-        server.wallet_manager.create_wallet("delete_test", false).await.ok();
+        server.wallet_manager.create_wallet("delete_test", "test_password", false).await.ok();
         let app = server.create_router().await;
         
         let request = Request::builder()
@@ -102,7 +103,7 @@ mod server_integration_tests {
     #[tokio::test]
     async fn test_route_wallet_balance() {
         let server = create_test_server().await;
-        server.wallet_manager.create_wallet("balance_test", false).await.ok();
+        server.wallet_manager.create_wallet("balance_test", "test_password", false).await.ok();
         let app = server.create_router().await;
         
         let request = Request::builder()
@@ -121,7 +122,7 @@ mod server_integration_tests {
     #[tokio::test]
     async fn test_route_tx_send() {
         let server = create_test_server().await;
-        server.wallet_manager.create_wallet("tx_test", false).await.ok();
+        server.wallet_manager.create_wallet("tx_test", "test_password", false).await.ok();
         let app = server.create_router().await;
         
         let payload = serde_json::json!({
@@ -145,7 +146,7 @@ mod server_integration_tests {
     #[tokio::test]
     async fn test_route_tx_history() {
         let server = create_test_server().await;
-        server.wallet_manager.create_wallet("history_test", false).await.ok();
+        server.wallet_manager.create_wallet("history_test", "test_password", false).await.ok();
         let app = server.create_router().await;
         
         let request = Request::builder()
@@ -162,7 +163,7 @@ mod server_integration_tests {
     #[tokio::test]
     async fn test_route_tx_multisig() {
         let server = create_test_server().await;
-        server.wallet_manager.create_wallet("multisig_test", false).await.ok();
+        server.wallet_manager.create_wallet("multisig_test", "test_password", false).await.ok();
         let app = server.create_router().await;
         
         let payload = serde_json::json!({
@@ -217,7 +218,7 @@ mod server_integration_tests {
     #[tokio::test]
     async fn test_route_backup_create() {
         let server = create_test_server().await;
-        server.wallet_manager.create_wallet("backup_test", false).await.ok();
+        server.wallet_manager.create_wallet("backup_test", "test_password", false).await.ok();
         let app = server.create_router().await;
         
         let request = Request::builder()
@@ -522,7 +523,7 @@ mod server_integration_tests {
         for i in 0..10 {
             let wallet_manager = server.wallet_manager.clone();
             let handle = tokio::spawn(async move {
-                wallet_manager.create_wallet(&format!("concurrent_{}", i), false).await
+                wallet_manager.create_wallet(&format!("concurrent_{}", i), "test_password", false).await
             });
             handles.push(handle);
         }
@@ -564,7 +565,7 @@ mod server_integration_tests {
     #[tokio::test]
     async fn test_validation_invalid_address() {
         let server = create_test_server().await;
-        server.wallet_manager.create_wallet("validation_test", false).await.ok();
+        server.wallet_manager.create_wallet("validation_test", "test_password", false).await.ok();
         let app = server.create_router().await;
         
         let payload = serde_json::json!({
@@ -582,14 +583,14 @@ mod server_integration_tests {
             .unwrap();
         
         let response = app.oneshot(request).await.unwrap();
-        // Validation errors return 422 (UNPROCESSABLE_ENTITY) in this implementation
-        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        // The request extractors reject invalid fields with 400 before the handler runs
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
     
     #[tokio::test]
     async fn test_validation_negative_amount() {
         let server = create_test_server().await;
-        server.wallet_manager.create_wallet("amount_test", false).await.ok();
+        server.wallet_manager.create_wallet("amount_test", "test_password", false).await.ok();
         let app = server.create_router().await;
         
         let payload = serde_json::json!({
@@ -607,8 +608,8 @@ mod server_integration_tests {
             .unwrap();
         
         let response = app.oneshot(request).await.unwrap();
-        // Validation errors return 422 (UNPROCESSABLE_ENTITY) in this implementation
-        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        // The request extractors reject invalid fields with 400 before the handler runs
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 }
 
@@ -17,6 +17,7 @@ fn create_test_config() -> WalletConfig {
             database_url: "sqlite::memory:".to_string(), // 淇锛氱Щ闄?//
             max_connections: Some(1),
             connection_timeout_seconds: Some(30),
+            read_database_url: None,
         },
         blockchain: BlockchainConfig {
             networks: HashMap::new(),
@@ -25,6 +26,27 @@ fn create_test_config() -> WalletConfig {
         multi_sig_threshold: 2,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     }
 }
 
@@ -97,7 +119,7 @@ async fn test_create_wallet_branches() {
     let res = server.post("/api/wallets").json(&payload).await;
     assert_eq!(res.status_code(), StatusCode::UNAUTHORIZED);
     let err: Value = res.json();
-    assert_eq!(err["error"], "Unauthorized");
+    assert_eq!(err["error"]["message"], "Unauthorized");
 
     // invalid name (contains hyphen)
     let payload2 = json!({ "name": "bad-name", "quantum_safe": false });
@@ -108,7 +130,7 @@ async fn test_create_wallet_branches() {
         .await;
     assert_eq!(res2.status_code(), StatusCode::BAD_REQUEST);
     let err2: Value = res2.json();
-    assert_eq!(err2["error"], "Invalid wallet name");
+    assert_eq!(err2["error"]["message"], "Invalid wallet name");
 
     // success
     let name = format!("w_{}", Uuid::new_v4().simple());
@@ -177,14 +199,14 @@ async fn test_delete_wallet_branches() {
         server.delete("/api/wallets/bad-name").add_header("Authorization", "test_api_key").await;
     assert_eq!(res2.status_code(), StatusCode::BAD_REQUEST);
     let err: Value = res2.json();
-    assert_eq!(err["error"], "Invalid wallet name");
+    assert_eq!(err["error"]["message"], "Invalid wallet name");
 
     // not found
     let res3 =
         server.delete("/api/wallets/not_exist").add_header("Authorization", "test_api_key").await;
     assert_eq!(res3.status_code(), StatusCode::NOT_FOUND);
     let err3: Value = res3.json();
-    assert_eq!(err3["error"], "Wallet not found");
+    assert_eq!(err3["error"]["message"], "Wallet not found");
 
     // success
     let name = format!("del_{}", Uuid::new_v4().simple());
@@ -225,7 +247,7 @@ async fn test_get_balance_branches() {
         .await;
     assert_eq!(r2.status_code(), StatusCode::BAD_REQUEST);
     let e: Value = r2.json();
-    assert_eq!(e["error"], "Network parameter is required");
+    assert_eq!(e["error"]["message"], "Network parameter is required");
 
     // wallet not found
     let r3 = server
@@ -234,7 +256,7 @@ async fn test_get_balance_branches() {
         .await;
     assert_eq!(r3.status_code(), StatusCode::NOT_FOUND);
     let e3: Value = r3.json();
-    assert_eq!(e3["error"], "Wallet not found");
+    assert_eq!(e3["error"]["message"], "Wallet not found");
 
     // create wallet then call -> but no blockchain client configured -> expect 500
     let name = format!("bal_{}", Uuid::new_v4().simple());
@@ -282,7 +304,7 @@ async fn send_transaction_branches() {
         .await;
     assert_eq!(r2.status_code(), StatusCode::NOT_FOUND);
     let e: Value = r2.json();
-    assert_eq!(e["error"], "Wallet not found");
+    assert_eq!(e["error"]["message"], "Wallet not found");
 
     // invalid address format for eth - but wallet doesn't exist, so wallet not found first
     let r3 = server
@@ -292,7 +314,7 @@ async fn send_transaction_branches() {
         .await;
     assert_eq!(r3.status_code(), StatusCode::NOT_FOUND);
     let e3: Value = r3.json();
-    assert_eq!(e3["error"], "Wallet not found");
+    assert_eq!(e3["error"]["message"], "Wallet not found");
 
     // invalid amount - but wallet doesn't exist, so wallet not found first
     let r4 = server
@@ -302,7 +324,7 @@ async fn send_transaction_branches() {
         .await;
     assert_eq!(r4.status_code(), StatusCode::NOT_FOUND);
     let e4: Value = r4.json();
-    assert_eq!(e4["error"], "Wallet not found");
+    assert_eq!(e4["error"]["message"], "Wallet not found");
 
     // wallet not found
     let r5 = server
@@ -312,7 +334,7 @@ async fn send_transaction_branches() {
         .await;
     assert_eq!(r5.status_code(), StatusCode::NOT_FOUND);
     let e5: Value = r5.json();
-    assert_eq!(e5["error"], "Wallet not found");
+    assert_eq!(e5["error"]["message"], "Wallet not found");
 
     // create wallet and attempt to send -> no blockchain client -> expect 500
     let name = format!("send_{}", Uuid::new_v4().simple());
@@ -449,8 +471,12 @@ async fn multi_sig_branches() {
     create_test_wallet(&server, &name).await;
 
     // insufficient signatures
-    let payload =
-        json!({ "to_address": "0xabc", "amount": "1.0", "network": "eth", "signatures": ["sig1"] });
+    let payload = json!({
+        "to_address": "0x742d35Cc6634C0532925a3b844Bc454e4438f44e",
+        "amount": "1.0",
+        "network": "eth",
+        "signatures": ["sig1"]
+    });
     let r = server
         .post(&format!("/api/wallets/{}/send_multi_sig", name))
         .json(&payload)
@@ -458,9 +484,9 @@ async fn multi_sig_branches() {
         .await;
     assert_eq!(r.status_code(), StatusCode::BAD_REQUEST);
     let e: Value = r.json();
-    assert_eq!(e["error"], "Insufficient signatures");
+    assert_eq!(e["error"]["message"], "Insufficient signatures");
 
-    // sufficient signatures -> should fail on invalid address
+    // invalid address is rejected before the signature check
     let payload2 = json!({ "to_address": "0xabc", "amount": "1.0", "network": "eth", "signatures": ["sig1","sig2"] });
     let r2 = server
         .post(&format!("/api/wallets/{}/send_multi_sig", name))
@@ -469,7 +495,7 @@ async fn multi_sig_branches() {
         .await;
     assert_eq!(r2.status_code(), StatusCode::BAD_REQUEST);
     let e2: Value = r2.json();
-    assert_eq!(e2["error"], "Invalid address: Invalid Ethereum address format");
+    assert_eq!(e2["error"]["code"], "INVALID_ADDRESS");
 }
 
 #[tokio::test]
@@ -510,7 +536,7 @@ async fn bridge_all_branches_including_concurrent() {
         server.post("/api/bridge").json(&bad).add_header("Authorization", "test_api_key").await;
     assert_eq!(r2.status_code(), StatusCode::BAD_REQUEST);
     let e: Value = r2.json();
-    assert_eq!(e["error"], "Invalid parameters");
+    assert_eq!(e["error"]["code"], "INVALID_WALLET_NAME");
 
     // invalid amount
     let bad2 = json!({ "from_wallet": "w", "from_chain": "eth", "to_chain": "polygon", "token": "USDC", "amount": "-1" });
@@ -518,7 +544,7 @@ async fn bridge_all_branches_including_concurrent() {
         server.post("/api/bridge").json(&bad2).add_header("Authorization", "test_api_key").await;
     assert_eq!(r3.status_code(), StatusCode::BAD_REQUEST);
     let e3: Value = r3.json();
-    assert_eq!(e3["error"], "Invalid amount");
+    assert_eq!(e3["error"]["code"], "INVALID_AMOUNT");
 
     // unsupported chain
     let bad3 = json!({ "from_wallet": "w", "from_chain": "btc", "to_chain": "polygon", "token": "USDC", "amount": "1" });
@@ -526,14 +552,14 @@ async fn bridge_all_branches_including_concurrent() {
         server.post("/api/bridge").json(&bad3).add_header("Authorization", "test_api_key").await;
     assert_eq!(r4.status_code(), StatusCode::BAD_REQUEST);
     let e4: Value = r4.json();
-    assert_eq!(e4["error"], "Unsupported chain");
+    assert_eq!(e4["error"]["message"], "Unsupported chain");
 
     // wallet not found
     let bf = json!({ "from_wallet": "noexist", "from_chain": "eth", "to_chain": "polygon", "token": "USDC", "amount": "1.0" });
     let r5 = server.post("/api/bridge").json(&bf).add_header("Authorization", "test_api_key").await;
     assert_eq!(r5.status_code(), StatusCode::NOT_FOUND);
     let e5: Value = r5.json();
-    assert_eq!(e5["error"], "Wallet not found");
+    assert_eq!(e5["error"]["message"], "Wallet not found");
 
     // success path: create wallet then bridge
     let name = format!("br_{}", Uuid::new_v4().simple());
@@ -214,8 +214,11 @@ mod api_server_unit_tests {
         
         let response = app.oneshot(request).await.unwrap();
         
-        // 应该不返回 404 Not Found
-        assert_ne!(response.status(), StatusCode::NOT_FOUND);
+        // 名称合法而wallet不存在时 handler 返回 WALLET_NOT_FOUND；路由缺失的 404 没有 body
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8_lossy(&body);
+        assert!(status != StatusCode::NOT_FOUND || body.contains("WALLET_NOT_FOUND"), "{}: {}", status, body);
     }
     
     #[tokio::test]
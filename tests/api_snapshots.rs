@@ -0,0 +1,268 @@
+//! API 响应快照（golden files）
+//!
+//! 固定时钟 + 顺序 ID 构造 fixture，把 GET 端点和代表性错误响应的
+//! `{status, body}` 与 `tests/fixtures/snapshots/<name>.json` 逐字节比较。
+//! 响应形状的任何变化都会让 `cargo test snapshots` failed；确认变化是预期的
+//! 之后用 `UPDATE_SNAPSHOTS=1 cargo test snapshots` 重写 golden 文件并一并提交。
+
+mod util;
+
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use chrono::TimeZone;
+use serde_json::{json, Map, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use defi_hot_wallet::core::clock::FixedClock;
+use defi_hot_wallet::core::ids::SequentialIds;
+use defi_hot_wallet::security::approval_key::ApprovalKey;
+use defi_hot_wallet::storage::{ApprovalPayload, ApprovalRecord, TransactionRecord, APPROVAL_PENDING};
+
+const API_KEY: &str = "snapshot-admin-key";
+const OWNER_TOKEN: &str = "snapshot-owner-token";
+const NOW: i64 = 1_700_000_000;
+const WALLET: &str = "treasury";
+const TREASURY_ADDRESS: &str = "0x1111111111111111111111111111111111111111";
+const OPS_ADDRESS: &str = "0x2222222222222222222222222222222222222222";
+const TO: &str = "0x000000000000000000000000000000000000dead";
+const APPROVAL_ID: &str = "00000000-0000-0000-0000-0000000000a1";
+const TX_ID: &str = "00000000-0000-0000-0000-0000000000b1";
+const TX_HASH: &str = "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+
+/// 只为占一行 pending 审批，不会被打开
+fn placeholder_key() -> ApprovalKey {
+    ApprovalKey { salt: vec![0; 32], nonce: vec![0; 12], ciphertext: vec![0; 48] }
+}
+
+// ---- golden files ----
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/snapshots").join(format!("{}.json", name))
+}
+
+/// 键按字典序递归排序，两空格缩进，LF 结尾；不依赖 serde_json 的 map 实现
+fn canonical(value: &Value) -> String {
+    fn sort(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                let mut sorted = Map::new();
+                for k in keys {
+                    sorted.insert(k.clone(), sort(&map[k]));
+                }
+                Value::Object(sorted)
+            }
+            Value::Array(items) => Value::Array(items.iter().map(sort).collect()),
+            other => other.clone(),
+        }
+    }
+    let mut out = serde_json::to_string_pretty(&sort(value)).unwrap();
+    out.push('\n');
+    out
+}
+
+fn assert_snapshot(name: &str, res: &TestResponse) {
+    let body = if res.text().is_empty() { Value::Null } else { res.json::<Value>() };
+    let actual = canonical(&json!({ "status": res.status_code().as_u16(), "body": body }));
+    let path = snapshot_path(name);
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+    // Windows 上 checkout 可能带 CRLF
+    let expected = std::fs::read_to_string(&path).map(|s| s.replace("\r\n", "\n")).unwrap_or_default();
+    if expected != actual {
+        let new_path = path.with_extension("json.new");
+        std::fs::write(&new_path, &actual).unwrap();
+        panic!(
+            "snapshot `{}` does not match {}\n\n--- expected\n{}\n--- actual\n{}\nreview {} and rerun with UPDATE_SNAPSHOTS=1 to accept",
+            name,
+            path.display(),
+            expected,
+            actual,
+            new_path.display()
+        );
+    }
+}
+
+// ---- fixture ----
+
+struct Fixture {
+    app: TestServer,
+    _dir: tempfile::TempDir,
+}
+
+/// 两个wallet、一个审查阈值、一个挂起审批、一条交易记录，全部走固定时钟
+async fn fixture() -> Fixture {
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server_with_sources(
+        dir.path(),
+        util::memory_config(),
+        Some(API_KEY),
+        Arc::new(FixedClock::at(NOW)),
+        Arc::new(SequentialIds::new()),
+    )
+    .await;
+
+    let owner_id = util::sign_in(&server, "owner@example.com", OWNER_TOKEN).await;
+    server.user_db.link_wallet(&owner_id, WALLET, TREASURY_ADDRESS, None).await.unwrap();
+    server.user_db.link_wallet(&owner_id, "ops", OPS_ADDRESS, Some("multisig")).await.unwrap();
+
+    let storage = &server.storage;
+    storage.store_wallet(WALLET, b"snapshot-wallet", false).await.unwrap();
+    let wallet_id = storage.wallet_id(WALLET).await.unwrap().unwrap();
+    storage.set_review_threshold(WALLET, Some("5"), "admin").await.unwrap();
+    storage
+        .create_approval(
+            &ApprovalRecord {
+                id: APPROVAL_ID.to_string(),
+                wallet_name: WALLET.to_string(),
+                network: "eth".to_string(),
+                payload: ApprovalPayload {
+                    from: TREASURY_ADDRESS.to_string(),
+                    to: TO.to_string(),
+                    value: "7500000000000000000".to_string(),
+                    token: None,
+                    allow_duplicate: false,
+                },
+                amount: "7.5".to_string(),
+                requested_by: "admin".to_string(),
+                token_id: None,
+                status: APPROVAL_PENDING.to_string(),
+                decided_by: None,
+                reason: None,
+                tx_hash: None,
+                created_at: NOW,
+                expires_at: NOW + 3600,
+                decided_at: None,
+            },
+            &placeholder_key(),
+        )
+        .await
+        .unwrap();
+    storage
+        .store_transaction(&TransactionRecord {
+            id: TX_ID.to_string(),
+            wallet_id,
+            tx_hash: TX_HASH.to_string(),
+            network: "eth".to_string(),
+            from_address: TREASURY_ADDRESS.to_string(),
+            to_address: TO.to_string(),
+            amount: "1.0".to_string(),
+            fee: "0.000063".to_string(),
+            status: "pending".to_string(),
+            created_at: chrono::Utc.timestamp_opt(NOW, 0).unwrap(),
+            confirmed_at: None,
+            integrity_hash: String::new(),
+            fee_actual: None,
+            block_number: None,
+        })
+        .await
+        .unwrap();
+
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Fixture { app, _dir: dir }
+}
+
+// ---- snapshots ----
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_snapshots_get_endpoints() {
+    let f = fixture().await;
+    let session = format!("Bearer {}", OWNER_TOKEN);
+
+    let res = f.app.get("/api/wallets").add_header("Authorization", session.clone()).await;
+    assert_snapshot("wallets_list", &res);
+
+    let res = f.app.get(&format!("/api/wallets/{}/review_threshold", WALLET)).add_header("X-API-KEY", API_KEY).await;
+    assert_snapshot("review_threshold_get", &res);
+
+    let res = f.app.get("/api/wallets/ops/review_threshold").add_header("Authorization", session).await;
+    assert_snapshot("review_threshold_unset", &res);
+
+    let res =
+        f.app.get("/api/approvals").add_header("X-API-KEY", API_KEY).add_query_param("status", "pending").await;
+    assert_snapshot("approvals_pending", &res);
+
+    let res = f.app.get(&format!("/api/transactions/{}/status", TX_HASH)).add_header("X-API-KEY", API_KEY).await;
+    assert_snapshot("transaction_status", &res);
+
+    let res = f.app.get("/api/events").add_header("X-API-KEY", API_KEY).add_query_param("after_seq", 0).await;
+    assert_snapshot("events_page", &res);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_snapshots_error_responses() {
+    let f = fixture().await;
+
+    // 错误 body 带 request_id，给定固定值使快照可复现
+    let res = f.app.get("/api/events").add_header("X-Request-Id", "snapshot-events-unauthorized").await;
+    assert_eq!(res.status_code(), StatusCode::UNAUTHORIZED);
+    assert_snapshot("error_events_unauthorized", &res);
+
+    let res = f.app.get("/api/wallets").add_header("X-Request-Id", "snapshot-wallets-no-session").await;
+    assert_snapshot("error_wallets_no_session", &res);
+
+    let res = f
+        .app
+        .get("/api/wallets")
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .add_header("X-Request-Id", "snapshot-wallets-invalid-cursor")
+        .add_query_param("cursor", "not-a-cursor")
+        .await;
+    assert_snapshot("error_wallets_invalid_cursor", &res);
+
+    let res = f
+        .app
+        .get("/api/wallets/bad!name/review_threshold")
+        .add_header("X-API-KEY", API_KEY)
+        .add_header("X-Request-Id", "snapshot-invalid-wallet-name")
+        .await;
+    assert_snapshot("error_invalid_wallet_name", &res);
+
+    let res = f
+        .app
+        .get("/api/approvals")
+        .add_header("X-API-KEY", API_KEY)
+        .add_header("X-Request-Id", "snapshot-approvals-invalid-status")
+        .add_query_param("status", "done")
+        .await;
+    assert_snapshot("error_approvals_invalid_status", &res);
+
+    // 会话user不在审批人名单中
+    let res = f
+        .app
+        .get("/api/approvals")
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .add_header("X-Request-Id", "snapshot-approvals-not-approver")
+        .await;
+    assert_snapshot("error_approvals_not_approver", &res);
+
+    let res = f
+        .app
+        .get("/api/transactions/0x1234/status")
+        .add_header("X-API-KEY", API_KEY)
+        .add_header("X-Request-Id", "snapshot-transaction-invalid-hash")
+        .await;
+    assert_snapshot("error_transaction_invalid_hash", &res);
+}
+
+/// 两次独立构造的 fixture 必须产生逐字节相同的输出
+#[tokio::test]
+#[serial_test::serial]
+async fn test_snapshots_are_reproducible() {
+    let mut outputs = Vec::new();
+    for _ in 0..2 {
+        let f = fixture().await;
+        let events = f.app.get("/api/events").add_header("X-API-KEY", API_KEY).await;
+        let approvals = f.app.get("/api/approvals").add_header("X-API-KEY", API_KEY).await;
+        outputs.push(canonical(&json!([events.json::<Value>(), approvals.json::<Value>()])));
+    }
+    assert_eq!(outputs[0], outputs[1]);
+}
@@ -13,6 +13,7 @@ fn create_test_config() -> WalletConfig {
             database_url: "sqlite::memory:".to_string(), // 淇锛氱Щ闄?//
             max_connections: Some(1),
             connection_timeout_seconds: Some(30),
+            read_database_url: None,
         },
         blockchain: BlockchainConfig {
             networks: HashMap::new(),
@@ -21,6 +22,27 @@ fn create_test_config() -> WalletConfig {
         multi_sig_threshold: 2,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     }
 }
 
@@ -0,0 +1,411 @@
+//! 四眼审批：审查阈值路由、自我审批、过期、余额重新validate与批准后广播
+
+mod util;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::abi::{self, Token};
+use ethers::providers::{MockProvider, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, Eip1559TransactionRequest, TransactionReceipt, H256, U256};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::approvals::ApprovalQueue;
+use defi_hot_wallet::blockchain::gas_oracle::ProviderGasOracle;
+use defi_hot_wallet::core::abi::selector_from_signature;
+use defi_hot_wallet::core::config::{ApprovalConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::storage::journal_events::{APPROVAL_REQUESTED, APPROVAL_STATUS_CHANGED};
+use defi_hot_wallet::storage::WalletStorage;
+
+const API_KEY: &str = "approval-workflow-admin-key";
+const REQUESTER_TOKEN: &str = "approval-requester-token";
+const APPROVER_TOKEN: &str = "approval-approver-token";
+const APPROVER_EMAIL: &str = "approver@example.com";
+const WALLET: &str = "treasury";
+const PASSWORD: &str = "Tr3asury!Vault#2024";
+const TO: &str = "0x000000000000000000000000000000000000dead";
+const GWEI: u64 = 1_000_000_000;
+const ETHER: u64 = 1_000_000_000_000_000_000;
+const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+/// 节点：只记录广播次数
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    broadcasts: Mutex<usize>,
+}
+
+impl MockChain {
+    fn broadcasts(&self) -> usize {
+        *self.broadcasts.lock().unwrap()
+    }
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3 * GWEI);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        *self.broadcasts.lock().unwrap() += 1;
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        _tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        Ok(None)
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    chain: Arc<MockChain>,
+    /// 批准时的余额重新check：先 gas price 后余额（LIFO，先推余额）
+    rpc: MockProvider,
+    storage: Arc<WalletStorage>,
+    _dir: tempfile::TempDir,
+}
+
+/// `requester_is_approver`：请求方本人也在审批人名单中
+async fn build(expiry_secs: u64, requester_is_approver: bool) -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let mut approvers = vec![APPROVER_EMAIL.to_string()];
+    if requester_is_approver {
+        approvers.push("requester@example.com".to_string());
+    }
+    let config = WalletConfig { approvals: ApprovalConfig { approvers, expiry_secs }, ..util::memory_config() };
+    let chain = Arc::new(MockChain::default());
+    let (provider, rpc) = Provider::mocked();
+    let server = util::test_server(dir.path(), config, Some(API_KEY))
+        .await
+        .with_broadcast_chain(chain.clone())
+        .with_gas_oracle(Arc::new(ProviderGasOracle::<MockProvider>::new().with_provider("eth", provider)));
+
+    let mut user_ids = Vec::new();
+    for (email, token) in [("requester@example.com", REQUESTER_TOKEN), (APPROVER_EMAIL, APPROVER_TOKEN)] {
+        user_ids.push(util::sign_in(&server, email, token).await);
+    }
+
+    util::create_wallet(&server, WALLET, PASSWORD).await;
+    let address = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+    server.user_db.link_wallet(&user_ids[0], WALLET, &format!("{:#x}", address), None).await.unwrap();
+
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    let res = app
+        .put(&format!("/api/wallets/{}/review_threshold", WALLET))
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "threshold": "1.0" }))
+        .await;
+    res.assert_status_ok();
+
+    Harness { app, chain, rpc, storage, _dir: dir }
+}
+
+impl Harness {
+    async fn send(&self, amount: &str) -> axum_test::TestResponse {
+        self.app
+            .post(&format!("/api/wallets/{}/send", WALLET))
+            .add_header("Authorization", format!("Bearer {}", REQUESTER_TOKEN))
+            .json(&json!({ "to": TO, "amount": amount, "network": "eth", "password": PASSWORD }))
+            .await
+    }
+
+    /// 发送超过阈值的金额并返回 approval id
+    async fn hold(&self, amount: &str) -> String {
+        let res = self.send(amount).await;
+        res.assert_status(axum::http::StatusCode::ACCEPTED);
+        let body: Value = res.json();
+        assert_eq!(body["status"], "pending_approval");
+        body["approval_id"].as_str().unwrap().to_string()
+    }
+
+    async fn approve(&self, id: &str, token: &str) -> axum_test::TestResponse {
+        self.app
+            .post(&format!("/api/approvals/{}/approve", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await
+    }
+
+    async fn status_of(&self, id: &str) -> String {
+        let res = self
+            .app
+            .get("/api/approvals")
+            .add_header("X-API-KEY", API_KEY)
+            .await;
+        res.assert_status_ok();
+        let body: Value = res.json();
+        let approval = body["approvals"].as_array().unwrap().iter().find(|a| a["id"] == id).cloned().unwrap();
+        approval["status"].as_str().unwrap().to_string()
+    }
+
+    fn push_balance(&self, balance: U256, gas_price: U256) {
+        self.rpc.push(balance).unwrap();
+        self.rpc.push(gas_price).unwrap();
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_sends_above_threshold_are_held() {
+    let h = build(3600, false).await;
+
+    // 阈值只能由 admin 设置
+    let res = h
+        .app
+        .put(&format!("/api/wallets/{}/review_threshold", WALLET))
+        .add_header("Authorization", format!("Bearer {}", REQUESTER_TOKEN))
+        .json(&json!({ "threshold": null }))
+        .await;
+    res.assert_status_unauthorized();
+    let res = h
+        .app
+        .get(&format!("/api/wallets/{}/review_threshold", WALLET))
+        .add_header("Authorization", format!("Bearer {}", REQUESTER_TOKEN))
+        .await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["threshold"], "1.0");
+
+    // 等于阈值：直接发送
+    h.send("1.0").await.assert_status_ok();
+    assert_eq!(h.chain.broadcasts(), 1);
+
+    let id = h.hold("2.5").await;
+    assert_eq!(h.chain.broadcasts(), 1);
+
+    // 请求方不是审批人
+    let res = h
+        .app
+        .get("/api/approvals")
+        .add_header("Authorization", format!("Bearer {}", REQUESTER_TOKEN))
+        .add_query_param("status", "pending")
+        .await;
+    res.assert_status_forbidden();
+    assert_eq!(res.json::<Value>()["error"]["code"], "NOT_AN_APPROVER");
+
+    let res = h
+        .app
+        .get("/api/approvals")
+        .add_header("Authorization", format!("Bearer {}", APPROVER_TOKEN))
+        .add_query_param("status", "pending")
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    let pending = body["approvals"].as_array().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0]["id"], id.as_str());
+    assert_eq!(pending[0]["amount"], "2.5");
+    assert_eq!(pending[0]["payload"]["to"], TO);
+    assert_eq!(pending[0]["payload"]["value"], (U256::from(ETHER) * 5u64 / 2u64).to_string());
+
+    let events = h.storage.journal_events(0, 100).await.unwrap();
+    assert!(events.iter().any(|e| e.event_type == APPROVAL_REQUESTED && e.entity_id == id));
+    let audit = h.storage.get_audit_logs(Some(WALLET)).await.unwrap();
+    assert!(audit.iter().any(|a| a.action == "approval.requested"));
+}
+
+/// 外部sign的 mainnet EIP-1559 transaction
+fn signed_tx(to: &str, value: U256, data: Vec<u8>) -> String {
+    let wallet = LocalWallet::from_bytes(&[0x07; 32]).unwrap().with_chain_id(1u64);
+    let tx: TypedTransaction = Eip1559TransactionRequest::new()
+        .to(to.parse::<Address>().unwrap())
+        .value(value)
+        .data(data)
+        .gas(60_000u64)
+        .max_fee_per_gas(20 * GWEI)
+        .max_priority_fee_per_gas(GWEI)
+        .nonce(0u64)
+        .chain_id(1u64)
+        .into();
+    let signature = wallet.sign_transaction_sync(&tx).unwrap();
+    format!("0x{}", hex::encode(tx.rlp_signed(&signature)))
+}
+
+fn transfer(token_to: &str, amount: u64) -> Vec<u8> {
+    let mut data = selector_from_signature("transfer(address,uint256)").to_vec();
+    data.extend(abi::encode(&[Token::Address(token_to.parse().unwrap()), Token::Uint(U256::from(amount))]));
+    data
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_signed_send_is_reviewed_by_the_decoded_amount() {
+    let h = build(3600, false).await;
+    let send_signed = |to: &'static str, signed_tx: String| {
+        h.app
+            .post(&format!("/api/wallets/{}/send", WALLET))
+            .add_header("Authorization", format!("Bearer {}", REQUESTER_TOKEN))
+            .json(&json!({ "to": to, "amount": "0.01", "network": "eth", "signed_tx": signed_tx }))
+    };
+
+    // 声明 0.01，实际转出 2 ETH；5 USDC（6 位小数）；未登记 token 的数量无法换算
+    let unknown = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    for (to, signed_tx) in [
+        (TO, signed_tx(TO, U256::from(ETHER) * 2u64, vec![])),
+        (USDC, signed_tx(USDC, U256::zero(), transfer(TO, 5_000_000))),
+        (unknown, signed_tx(unknown, U256::zero(), transfer(TO, 1))),
+    ] {
+        let res = send_signed(to, signed_tx).await;
+        res.assert_status_forbidden();
+        assert_eq!(res.json::<Value>()["error"]["code"], "REVIEW_REQUIRES_SERVER_SIGNING");
+    }
+    assert_eq!(h.chain.broadcasts(), 0);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_requester_cannot_approve_own_send() {
+    let h = build(3600, true).await;
+    let id = h.hold("5").await;
+
+    let res = h.approve(&id, REQUESTER_TOKEN).await;
+    res.assert_status_forbidden();
+    assert_eq!(res.json::<Value>()["error"]["code"], "SELF_APPROVAL_FORBIDDEN");
+    assert_eq!(h.status_of(&id).await, "pending");
+
+    // 拒绝必须给出理由
+    let reject = format!("/api/approvals/{}/reject", id);
+    let res = h
+        .app
+        .post(&reject)
+        .add_header("Authorization", format!("Bearer {}", APPROVER_TOKEN))
+        .json(&json!({ "reason": "  " }))
+        .await;
+    res.assert_status_bad_request();
+
+    let res = h
+        .app
+        .post(&reject)
+        .add_header("Authorization", format!("Bearer {}", APPROVER_TOKEN))
+        .json(&json!({ "reason": "unknown counterparty" }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["approval"]["status"], "rejected");
+    assert_eq!(body["approval"]["reason"], "unknown counterparty");
+
+    let res = h.approve(&id, APPROVER_TOKEN).await;
+    res.assert_status(axum::http::StatusCode::CONFLICT);
+    assert_eq!(res.json::<Value>()["error"]["code"], "APPROVAL_NOT_PENDING");
+    assert_eq!(h.chain.broadcasts(), 0);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_expired_approval_is_never_executed() {
+    let h = build(0, false).await;
+    let id = h.hold("5").await;
+    h.push_balance(U256::from(ETHER) * 100, U256::from(20 * GWEI));
+
+    let res = h.approve(&id, APPROVER_TOKEN).await;
+    res.assert_status(axum::http::StatusCode::GONE);
+    assert_eq!(res.json::<Value>()["error"]["code"], "APPROVAL_EXPIRED");
+    assert_eq!(h.status_of(&id).await, "expired");
+    assert_eq!(h.chain.broadcasts(), 0);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_approval_rechecks_balance() {
+    let h = build(3600, false).await;
+    let id = h.hold("5").await;
+
+    // 挂起期间余额被转走
+    h.push_balance(U256::from(ETHER), U256::from(20 * GWEI));
+    let res = h.approve(&id, APPROVER_TOKEN).await;
+    res.assert_status(axum::http::StatusCode::CONFLICT);
+    assert_eq!(res.json::<Value>()["error"]["code"], "INSUFFICIENT_FUNDS");
+    assert_eq!(h.status_of(&id).await, "pending");
+    assert_eq!(h.chain.broadcasts(), 0);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_approved_send_is_broadcast() {
+    let h = build(3600, false).await;
+    let id = h.hold("5").await;
+
+    h.push_balance(U256::from(ETHER) * 10, U256::from(20 * GWEI));
+    let res = h.approve(&id, APPROVER_TOKEN).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["approval"]["status"], "approved");
+    assert!(body["approval"]["decided_by"].is_string());
+    let tx_hash = body["approval"]["tx_hash"].as_str().unwrap().to_string();
+    assert_eq!(h.chain.broadcasts(), 1);
+    assert!(h.storage.transaction_by_hash(&tx_hash).await.unwrap().is_some());
+
+    // 已执行的请求不能再批准
+    let res = h.approve(&id, APPROVER_TOKEN).await;
+    res.assert_status(axum::http::StatusCode::CONFLICT);
+    assert_eq!(h.chain.broadcasts(), 1);
+
+    let events = h.storage.journal_events(0, 100).await.unwrap();
+    let changes: Vec<_> =
+        events.iter().filter(|e| e.event_type == APPROVAL_STATUS_CHANGED && e.entity_id == id).collect();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].payload["to_status"], "approved");
+    let audit = h.storage.get_audit_logs(Some(WALLET)).await.unwrap();
+    for action in ["approval.requested", "approval.approved", "approval.executed"] {
+        assert!(audit.iter().any(|a| a.action == action), "{action}");
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_approval_rechecks_network_allowlist() {
+    let h = build(3600, false).await;
+    let id = h.hold("5").await;
+
+    // 挂起期间 eth 被移出允许集合
+    let res = h
+        .app
+        .put(&format!("/api/wallets/{}/networks", WALLET))
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "allowed_networks": ["polygon"] }))
+        .await;
+    res.assert_status_ok();
+
+    let res = h.approve(&id, APPROVER_TOKEN).await;
+    res.assert_status(axum::http::StatusCode::FORBIDDEN);
+    assert_eq!(res.json::<Value>()["error"]["code"], "NETWORK_NOT_ALLOWED");
+    assert_eq!(h.status_of(&id).await, "pending");
+    assert_eq!(h.chain.broadcasts(), 0);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_another_instance_can_execute_the_approval() {
+    let h = build(3600, false).await;
+    let id = h.hold("5").await;
+    assert!(h.storage.approval_key(&id).await.unwrap().is_some());
+
+    // 共享同一数据库的另一个实例（或重启后的本实例）
+    let config = ApprovalConfig { approvers: vec![APPROVER_EMAIL.to_string()], expiry_secs: 3600 };
+    let queue = ApprovalQueue::new(config, h.storage.clone());
+    let record = queue.check(&id, "approver").await.unwrap();
+    let signer = queue.claim(&record, "approver").await.unwrap();
+    assert_eq!(format!("{:#x}", signer.address()), record.payload.from);
+
+    // 离开 pending 后密钥即删除
+    assert!(h.storage.approval_key(&id).await.unwrap().is_none());
+    assert_eq!(h.status_of(&id).await, "approved");
+}
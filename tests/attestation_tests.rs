@@ -0,0 +1,182 @@
+//! 地址控制权证明：签发、validate、过期与吊销
+
+mod util;
+
+use axum_test::TestServer;
+use chrono::Duration;
+use ethers::signers::Signer;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use defi_hot_wallet::core::clock::FixedClock;
+use defi_hot_wallet::core::ids::SequentialIds;
+
+const API_KEY: &str = "attestation-admin-key";
+const OWNER_TOKEN: &str = "attestation-owner-token";
+const WALLET: &str = "treasury";
+const PASSWORD: &str = "Attest!Vault#2024";
+const NOW: i64 = 1_700_000_000;
+
+struct Harness {
+    app: TestServer,
+    clock: Arc<FixedClock>,
+    address: String,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let clock = Arc::new(FixedClock::at(NOW));
+    let server = util::test_server_with_sources(
+        dir.path(),
+        util::memory_config(),
+        Some(API_KEY),
+        clock.clone(),
+        Arc::new(SequentialIds::new()),
+    )
+    .await;
+
+    let user_id = util::sign_in(&server, "attestation-owner@example.com", OWNER_TOKEN).await;
+    server.wallet_manager.create_wallet(WALLET, PASSWORD, false).await.unwrap();
+    let address = format!("{:#x}", server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address());
+    server.user_db.link_wallet(&user_id, WALLET, &address, None).await.unwrap();
+
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, clock, address, _dir: dir }
+}
+
+impl Harness {
+    async fn attest(&self, body: Value) -> Value {
+        let res = self
+            .app
+            .post(&format!("/api/wallets/{}/attestations", WALLET))
+            .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+            .json(&body)
+            .await;
+        res.assert_status_ok();
+        res.json()
+    }
+
+    async fn verify(&self, document: &Value) -> Value {
+        let res = self.app.post("/api/attestations/verify").add_header("X-API-KEY", API_KEY).json(document).await;
+        res.assert_status_ok();
+        res.json()
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_attestation_round_trip_and_tampering() {
+    let h = build().await;
+    let doc = h.attest(json!({ "password": PASSWORD, "audience": "exchange.example" })).await;
+    assert_eq!(doc["scheme"], "eip191");
+    assert_eq!(doc["network"], "eth");
+    assert_eq!(doc["address"].as_str().unwrap().to_lowercase(), h.address);
+    let challenge = doc["challenge"].as_str().unwrap();
+    assert!(challenge.contains("Audience: exchange.example"));
+    assert!(challenge.contains(doc["address"].as_str().unwrap()));
+
+    let result = h.verify(&doc).await;
+    assert_eq!(result["valid"], true);
+    assert_eq!(result["known"], true);
+    assert_eq!(result["managed"], true);
+    assert_eq!(result["wallet_name"], WALLET);
+
+    // 改动 challenge 后恢复出的是另一把密钥
+    let mut tampered = doc.clone();
+    tampered["challenge"] = json!(format!("{}\nAmount: 1000000", challenge));
+    let result = h.verify(&tampered).await;
+    assert_eq!(result["valid"], false);
+    assert_eq!(result["address_matches"], false);
+
+    // 声称的address不是签名者
+    let mut wrong_address = doc.clone();
+    wrong_address["address"] = json!("0x00000000000000000000000000000000000000aa");
+    assert_eq!(h.verify(&wrong_address).await["valid"], false);
+
+    // 调用方给的 challenge 原样签名
+    let doc = h.attest(json!({ "password": PASSWORD, "challenge": "exchange nonce 8f2a" })).await;
+    assert_eq!(doc["challenge"], "exchange nonce 8f2a");
+    assert_eq!(h.verify(&doc).await["valid"], true);
+
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/attestations", WALLET))
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .json(&json!({ "password": "wrong-password" }))
+        .await;
+    res.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_attestation_expiry_uses_stored_deadline() {
+    let h = build().await;
+    let doc = h.attest(json!({ "password": PASSWORD, "expires_in_secs": 3600 })).await;
+    assert_eq!(doc["expires_at"], NOW + 3600);
+    assert_eq!(h.verify(&doc).await["valid"], true);
+
+    // 文档里延长的有效期不被采信
+    let mut extended = doc.clone();
+    extended["expires_at"] = json!(NOW + 365 * 86_400);
+    h.clock.advance(Duration::hours(2));
+    let result = h.verify(&extended).await;
+    assert_eq!(result["signature_valid"], true);
+    assert_eq!(result["expired"], true);
+    assert_eq!(result["valid"], false);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_revoked_attestation_fails_verification() {
+    let h = build().await;
+    let doc = h.attest(json!({ "password": PASSWORD })).await;
+    let id = doc["id"].as_str().unwrap().to_string();
+
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/attestations/{}/revoke", WALLET, id))
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .json(&json!({ "reason": "key ceremony redo" }))
+        .await;
+    res.assert_status_ok();
+
+    // id 被改掉也按签名找到吊销记录
+    let mut renamed = doc.clone();
+    renamed["id"] = json!("someone-else");
+    let result = h.verify(&renamed).await;
+    assert_eq!(result["revoked"], true);
+    assert_eq!(result["valid"], false);
+
+    // 重复吊销为 404
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/attestations/{}/revoke", WALLET, id))
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .json(&json!({}))
+        .await;
+    res.assert_status_not_found();
+
+    let res = h
+        .app
+        .get(&format!("/api/wallets/{}/attestations", WALLET))
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .await;
+    res.assert_status_ok();
+    let list: Value = res.json();
+    assert_eq!(list["attestations"][0]["revoked_reason"], "key ceremony redo");
+}
+
+#[cfg(feature = "bitcoin")]
+#[tokio::test]
+#[serial_test::serial]
+async fn test_bitcoin_attestation_recovers_p2pkh_address() {
+    let h = build().await;
+    let doc = h.attest(json!({ "password": PASSWORD, "network": "btc" })).await;
+    assert_eq!(doc["scheme"], "bip137");
+    assert!(doc["address"].as_str().unwrap().starts_with('1'));
+
+    let result = h.verify(&doc).await;
+    assert_eq!(result["valid"], true);
+    assert_eq!(result["recovered_address"], doc["address"]);
+}
@@ -0,0 +1,171 @@
+//! 审计日志链：每行记录上一行的 MAC，删除整行（连同其 MAC）也能被发现；
+//! 链出现之前写入的旧行在迁移时以最早一行为创世行串起来
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::sqlite::SqlitePool;
+use tempfile::TempDir;
+
+use defi_hot_wallet::storage::WalletStorage;
+
+/// base64 of 32 zero bytes
+const KEY_B64: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+
+fn db_url(dir: &TempDir) -> String {
+    format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display())
+}
+
+async fn storage_with_actions(dir: &TempDir, count: usize) -> WalletStorage {
+    std::env::set_var("WALLET_ENC_KEY", KEY_B64);
+    let storage = WalletStorage::new_with_url(&db_url(dir)).await.unwrap();
+    for i in 0..count {
+        storage.log_action("treasury", "wallet_sent", &format!("send #{}", i), Some("10.0.0.1"), None).await.unwrap();
+    }
+    storage
+}
+
+async fn raw_pool(dir: &TempDir) -> SqlitePool {
+    SqlitePool::connect(&db_url(dir)).await.unwrap()
+}
+
+/// 删除一行审计记录及其 MAC
+async fn delete_entry(pool: &SqlitePool, id: i64) {
+    sqlx::query("DELETE FROM audit_logs_hmac WHERE audit_id = ?1").bind(id).execute(pool).await.unwrap();
+    sqlx::query("DELETE FROM audit_logs WHERE id = ?1").bind(id).execute(pool).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_intact_chain_verifies_every_entry() {
+    let dir = TempDir::new().unwrap();
+    let storage = storage_with_actions(&dir, 4).await;
+
+    let report = storage.verify_audit_chain().await.unwrap();
+    assert!(report.is_intact());
+    assert_eq!(report.total_entries, 4);
+    assert_eq!(report.verified, 4);
+    assert_eq!(storage.get_audit_logs(Some("treasury")).await.unwrap().len(), 4);
+
+    // 创世行不链接任何行，其余行链接前一行的 MAC
+    let links: Vec<(Option<String>, String)> =
+        sqlx::query_as("SELECT prev_mac, mac FROM audit_logs_hmac ORDER BY audit_id")
+            .fetch_all(&raw_pool(&dir).await)
+            .await
+            .unwrap();
+    assert_eq!(links[0].0, None);
+    for pair in links.windows(2) {
+        assert_eq!(pair[1].0.as_deref(), Some(pair[0].1.as_str()));
+    }
+}
+
+#[tokio::test]
+async fn test_deleted_row_breaks_the_chain_at_its_successor() {
+    let dir = TempDir::new().unwrap();
+    let storage = storage_with_actions(&dir, 5).await;
+    let pool = raw_pool(&dir).await;
+
+    // 每行自身的 MAC 仍然正确，只有链能发现缺了一行
+    delete_entry(&pool, 3).await;
+    let report = storage.verify_audit_chain().await.unwrap();
+    assert_eq!(report.total_entries, 4);
+    assert_eq!(report.verified, 2);
+    assert_eq!(report.broken_at, Some(4));
+    assert_eq!(report.reason.as_deref(), Some("does not link to the previous entry"));
+
+    let err = storage.get_audit_logs(None).await.unwrap_err().to_string();
+    assert!(err.contains("chain broken at id 4"), "{}", err);
+
+    // 把第 4 行的链接改指第 2 行也不行：链接受该行 MAC 保护
+    let second: String =
+        sqlx::query_scalar("SELECT mac FROM audit_logs_hmac WHERE audit_id = 2").fetch_one(&pool).await.unwrap();
+    sqlx::query("UPDATE audit_logs_hmac SET prev_mac = ?1 WHERE audit_id = 4")
+        .bind(second)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let report = storage.verify_audit_chain().await.unwrap();
+    assert_eq!(report.broken_at, Some(4));
+    assert_eq!(report.reason.as_deref(), Some("MAC mismatch"));
+}
+
+#[tokio::test]
+async fn test_deleted_genesis_is_detected() {
+    let dir = TempDir::new().unwrap();
+    let storage = storage_with_actions(&dir, 3).await;
+    delete_entry(&raw_pool(&dir).await, 1).await;
+
+    let report = storage.verify_audit_chain().await.unwrap();
+    assert_eq!(report.verified, 0);
+    assert_eq!(report.broken_at, Some(2));
+    assert_eq!(report.reason.as_deref(), Some("links to an entry before the genesis"));
+}
+
+/// 引入链之前的行 MAC：只覆盖行内容
+fn legacy_mac(id: i64, action: &str, details: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(&[0u8; 32]).unwrap();
+    mac.update(&id.to_le_bytes());
+    mac.update(b"treasury");
+    mac.update(action.as_bytes());
+    mac.update(details.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[tokio::test]
+async fn test_legacy_rows_are_linked_from_the_first_as_genesis() {
+    std::env::set_var("WALLET_ENC_KEY", KEY_B64);
+    let dir = TempDir::new().unwrap();
+    {
+        // 旧版本的表结构：audit_logs_hmac 没有 prev_mac 列
+        let pool = raw_pool(&dir).await;
+        sqlx::query(
+            "CREATE TABLE audit_logs (id INTEGER PRIMARY KEY AUTOINCREMENT, wallet_id TEXT, action TEXT NOT NULL, \
+             details TEXT, ip_address TEXT, user_agent TEXT, created_at DATETIME NOT NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "CREATE TABLE audit_logs_hmac (audit_id INTEGER PRIMARY KEY, mac TEXT NOT NULL, \
+             FOREIGN KEY(audit_id) REFERENCES audit_logs(id))",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for id in 1..=3i64 {
+            let details = format!("legacy #{}", id);
+            sqlx::query(
+                "INSERT INTO audit_logs (id, wallet_id, action, details, created_at) \
+                 VALUES (?1, 'treasury', 'wallet_created', ?2, '2024-01-01 00:00:00')",
+            )
+            .bind(id)
+            .bind(&details)
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("INSERT INTO audit_logs_hmac (audit_id, mac) VALUES (?1, ?2)")
+                .bind(id)
+                .bind(legacy_mac(id, "wallet_created", &details))
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        pool.close().await;
+    }
+
+    let storage = WalletStorage::new_with_url(&db_url(&dir)).await.unwrap();
+    let report = storage.verify_audit_chain().await.unwrap();
+    assert!(report.is_intact(), "{:?}", report);
+    assert_eq!(report.verified, 3);
+
+    // 创世行保留原 MAC，新行接在旧行之后
+    let pool = raw_pool(&dir).await;
+    let genesis: (Option<String>, String) =
+        sqlx::query_as("SELECT prev_mac, mac FROM audit_logs_hmac WHERE audit_id = 1").fetch_one(&pool).await.unwrap();
+    assert_eq!(genesis, (None, legacy_mac(1, "wallet_created", "legacy #1")));
+    storage.log_action("treasury", "wallet_sent", "after migration", None, None).await.unwrap();
+    assert_eq!(storage.get_audit_logs(None).await.unwrap().len(), 4);
+
+    // 旧行也受链保护；再次打开不会重复迁移
+    delete_entry(&pool, 2).await;
+    let reopened = WalletStorage::new_with_url(&db_url(&dir)).await.unwrap();
+    assert_eq!(reopened.verify_audit_chain().await.unwrap().broken_at, Some(3));
+}
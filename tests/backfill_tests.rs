@@ -0,0 +1,434 @@
+//! 链上历史回填：脚本化假链上的完整回填、中断后续跑不重复、原生币与 ERC-20
+//! 混合历史、回填账目与假链最终状态一致，以及 `/api/wallets/:name/backfill` 接口
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::types::{Address, Block, Bytes, Filter, Log, Transaction, TransactionReceipt, ValueOrArray, H256, U256, U64};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::history::{transfer_topic, HistoryRpc, RpcHistory};
+use defi_hot_wallet::core::config::{BackfillConfig, StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::ops::backfill::HistoryBackfill;
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{
+    LedgerBalance, NetworkInit, TransactionFilter, TransactionRecord, WalletStorage, NATIVE_TOKEN, SCAN_COMPLETE,
+};
+
+const NETWORK: &str = "eth";
+const WALLET_NAME: &str = "imported";
+const API_KEY: &str = "backfill-test-key-0123456789abcdef";
+const ETH: u128 = 1_000_000_000_000_000_000;
+const GAS_PRICE: u64 = 2_000_000_000;
+const FEE: i128 = 21_000 * GAS_PRICE as i128;
+
+fn wallet() -> Address {
+    Address::from_low_u64_be(0xa11ce)
+}
+
+fn funder() -> Address {
+    Address::from_low_u64_be(0xf00d)
+}
+
+fn other() -> Address {
+    Address::from_low_u64_be(0xb0b)
+}
+
+fn token() -> Address {
+    Address::from_low_u64_be(0x70ce)
+}
+
+/// 区块中的一笔操作；`Token` 由 `from` 发起，调用 [`token`] 合约
+enum Op {
+    Send { from: Address, to: Address, value: u128, success: bool },
+    Token { from: Address, to: Address, amount: u128 },
+}
+
+/// 按脚本出块的链，同时记下各地址的原生币与代币余额（手续费一律 21000 × 2 gwei）
+#[derive(Default)]
+struct FakeChain {
+    /// 下标即高度
+    blocks: Mutex<Vec<Block<Transaction>>>,
+    receipts: Mutex<HashMap<H256, TransactionReceipt>>,
+    logs: Mutex<Vec<Log>>,
+    native: Mutex<HashMap<Address, i128>>,
+    tokens: Mutex<HashMap<Address, i128>>,
+    minted: Mutex<u64>,
+    /// 读到该高度时失败一次
+    fail_at: Mutex<Option<u64>>,
+}
+
+impl FakeChain {
+    fn new() -> Arc<Self> {
+        let chain = Self::default();
+        chain.mine(&[]);
+        Arc::new(chain)
+    }
+
+    fn mine(&self, ops: &[Op]) -> u64 {
+        let mut blocks = self.blocks.lock().unwrap();
+        let number = blocks.len() as u64;
+        let mut native = self.native.lock().unwrap();
+        let mut tokens = self.tokens.lock().unwrap();
+        let mut logs = self.logs.lock().unwrap();
+        let mut transactions = Vec::new();
+        for op in ops {
+            let mut minted = self.minted.lock().unwrap();
+            *minted += 1;
+            let hash = H256::from_low_u64_be(*minted);
+            let (from, to, value, success) = match *op {
+                Op::Send { from, to, value, success } => (from, to, value, success),
+                Op::Token { from, .. } => (from, token(), 0, true),
+            };
+            transactions.push(Transaction {
+                hash,
+                from,
+                to: Some(to),
+                value: U256::from(value),
+                gas_price: Some(U256::from(GAS_PRICE)),
+                block_number: Some(U64::from(number)),
+                ..Default::default()
+            });
+            self.receipts.lock().unwrap().insert(
+                hash,
+                TransactionReceipt {
+                    transaction_hash: hash,
+                    block_number: Some(U64::from(number)),
+                    gas_used: Some(U256::from(21_000u64)),
+                    effective_gas_price: Some(U256::from(GAS_PRICE)),
+                    status: Some(U64::from(success as u64)),
+                    ..Default::default()
+                },
+            );
+            *native.entry(from).or_default() -= FEE;
+            if success {
+                *native.entry(from).or_default() -= value as i128;
+                *native.entry(to).or_default() += value as i128;
+            }
+            if let Op::Token { from, to, amount } = *op {
+                let mut data = [0u8; 32];
+                U256::from(amount).to_big_endian(&mut data);
+                let log_index = U256::from(logs.len());
+                logs.push(Log {
+                    address: token(),
+                    topics: vec![transfer_topic(), H256::from(from), H256::from(to)],
+                    data: Bytes::from(data.to_vec()),
+                    block_number: Some(U64::from(number)),
+                    transaction_hash: Some(hash),
+                    log_index: Some(log_index),
+                    ..Default::default()
+                });
+                *tokens.entry(from).or_default() -= amount as i128;
+                *tokens.entry(to).or_default() += amount as i128;
+            }
+        }
+        blocks.push(Block {
+            number: Some(U64::from(number)),
+            timestamp: U256::from(1_700_000_000 + 12 * number),
+            transactions,
+            ..Default::default()
+        });
+        number
+    }
+
+    fn native_balance(&self, address: Address) -> String {
+        self.native.lock().unwrap().get(&address).copied().unwrap_or_default().to_string()
+    }
+
+    fn token_balance(&self, address: Address) -> String {
+        self.tokens.lock().unwrap().get(&address).copied().unwrap_or_default().to_string()
+    }
+}
+
+fn topic(filter: &Filter, index: usize) -> Option<H256> {
+    match &filter.topics[index] {
+        Some(ValueOrArray::Value(Some(topic))) => Some(*topic),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl HistoryRpc for FakeChain {
+    async fn block_number(&self, _network: &str) -> Result<u64, WalletError> {
+        Ok(self.blocks.lock().unwrap().len() as u64 - 1)
+    }
+
+    async fn block_with_transactions(
+        &self,
+        _network: &str,
+        number: u64,
+    ) -> Result<Option<Block<Transaction>>, WalletError> {
+        let mut fail_at = self.fail_at.lock().unwrap();
+        if *fail_at == Some(number) {
+            *fail_at = None;
+            return Err(WalletError::NetworkError("connection reset".into()));
+        }
+        Ok(self.blocks.lock().unwrap().get(number as usize).cloned())
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        Ok(self.receipts.lock().unwrap().get(&tx_hash).cloned())
+    }
+
+    async fn logs(&self, _network: &str, filter: &Filter) -> Result<Vec<Log>, WalletError> {
+        let from = filter.get_from_block().unwrap().as_u64();
+        let to = filter.get_to_block().unwrap().as_u64();
+        let (sender, recipient) = (topic(filter, 1), topic(filter, 2));
+        Ok(self
+            .logs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|log| (from..=to).contains(&log.block_number.unwrap().as_u64()))
+            .filter(|log| sender.is_none_or(|t| log.topics[1] == t))
+            .filter(|log| recipient.is_none_or(|t| log.topics[2] == t))
+            .cloned()
+            .collect())
+    }
+}
+
+/// 区块 1..=8：入账、代币入账、转出、失败的转出、代币转出、自转与第二笔入账，
+/// 夹杂与wallet无关的transaction；涉及wallet的 7 笔transaction、8 条账目
+fn mixed_history() -> Arc<FakeChain> {
+    let chain = FakeChain::new();
+    chain.mine(&[
+        Op::Send { from: funder(), to: wallet(), value: 5 * ETH, success: true },
+        Op::Send { from: other(), to: funder(), value: ETH, success: true },
+    ]);
+    chain.mine(&[Op::Token { from: funder(), to: wallet(), amount: 1000 }]);
+    chain.mine(&[Op::Send { from: wallet(), to: other(), value: ETH, success: true }]);
+    chain.mine(&[Op::Send { from: wallet(), to: other(), value: 2 * ETH, success: false }]);
+    chain.mine(&[
+        Op::Token { from: wallet(), to: other(), amount: 250 },
+        Op::Token { from: funder(), to: other(), amount: 5 },
+    ]);
+    chain.mine(&[]);
+    chain.mine(&[Op::Send { from: wallet(), to: wallet(), value: ETH / 2, success: true }]);
+    chain.mine(&[Op::Send { from: other(), to: wallet(), value: ETH / 4, success: true }]);
+    chain
+}
+
+/// 只登记network，和非托管的 POST /api/wallets 一样不落库wallet
+async fn register_networks(storage: &WalletStorage) {
+    let init = NetworkInit {
+        network: NETWORK.to_string(),
+        address: format!("{:?}", wallet()),
+        nonce: Some(0),
+        scan_from_block: Some(0),
+        error: None,
+    };
+    storage.initialize_wallet_networks(WALLET_NAME, &[init]).await.unwrap();
+}
+
+async fn register_wallet(storage: &WalletStorage) {
+    // 落库wallet写审计记录，其 MAC 需要 WALLET_ENC_KEY
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    storage.store_wallet(WALLET_NAME, b"blob", false).await.unwrap();
+    register_networks(storage).await;
+}
+
+/// transactions 按 wallets.id 记录
+async fn records(storage: &WalletStorage) -> Vec<TransactionRecord> {
+    let wallet_id = storage.wallet_id(WALLET_NAME).await.unwrap().unwrap();
+    storage.get_wallet_transactions(&wallet_id).await.unwrap()
+}
+
+async fn setup(chain: Arc<FakeChain>) -> (Arc<WalletStorage>, HistoryBackfill) {
+    let storage = Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap());
+    register_wallet(&storage).await;
+    let config = BackfillConfig { blocks_per_batch: 3, ..Default::default() };
+    let backfill = HistoryBackfill::new(config, storage.clone(), Arc::new(RpcHistory::new(chain)));
+    (storage, backfill)
+}
+
+fn balance<'a>(balances: &'a [LedgerBalance], token: &str) -> &'a LedgerBalance {
+    balances.iter().find(|b| b.token == token).unwrap()
+}
+
+/// 回填账目的净额等于假链上wallet的最终余额（wallet在创世时余额为零）
+async fn assert_ledger_matches_chain(storage: &WalletStorage, chain: &FakeChain) {
+    let balances = storage.ledger_balances(WALLET_NAME, NETWORK).await.unwrap();
+    assert_eq!(balances.len(), 2);
+    assert_eq!(balance(&balances, NATIVE_TOKEN).net, chain.native_balance(wallet()));
+    assert_eq!(balance(&balances, &format!("{:?}", token())).net, chain.token_balance(wallet()));
+}
+
+#[tokio::test]
+async fn test_full_backfill_of_mixed_history_matches_chain() {
+    let chain = mixed_history();
+    let (storage, backfill) = setup(chain.clone()).await;
+
+    let cursor = backfill.request(WALLET_NAME, NETWORK, None).await.unwrap();
+    assert_eq!((cursor.from_block, cursor.next_block, cursor.target_block), (0, 0, 8));
+    assert_eq!(cursor.source, "rpc");
+
+    let report = backfill.run_once(&CancellationToken::new()).await;
+    assert_eq!((report.batches, report.entries, report.completed, report.errors), (3, 8, 1, 0));
+    let cursor = &storage.backfill_cursors(WALLET_NAME).await.unwrap()[0];
+    assert_eq!((cursor.status.as_str(), cursor.next_block, cursor.entries), (SCAN_COMPLETE, 9, 8));
+    assert!(cursor.completed_at.is_some());
+
+    assert_ledger_matches_chain(&storage, &chain).await;
+    let native = balance(&storage.ledger_balances(WALLET_NAME, NETWORK).await.unwrap(), NATIVE_TOKEN).clone();
+    assert_eq!(native.received, (5 * ETH + ETH / 4).to_string());
+    assert_eq!(native.sent, ETH.to_string(), "the failed send and the self transfer move nothing");
+    assert_eq!(native.fees, (4 * FEE).to_string(), "wallet sent four transactions");
+
+    // 每笔涉及wallet的transaction一条记录，代币转账也算
+    let transactions = records(&storage).await;
+    assert_eq!(transactions.len(), 7);
+    assert_eq!(transactions.iter().filter(|t| t.status == "failed").count(), 1);
+    let funder = format!("{:?}", funder());
+    assert!(transactions.iter().any(|t| t.from_address == funder && t.amount == "5.000000000000000000"));
+
+    let entries = storage.ledger_entries(WALLET_NAME, NETWORK).await.unwrap();
+    let token_out = entries.iter().find(|e| e.token != NATIVE_TOKEN && e.direction == "out").unwrap();
+    assert_eq!((token_out.amount.as_str(), token_out.fee.as_str()), ("250", "0"));
+}
+
+#[tokio::test]
+async fn test_interrupted_backfill_resumes_without_duplicates() {
+    let chain = mixed_history();
+    *chain.fail_at.lock().unwrap() = Some(4);
+    let (storage, backfill) = setup(chain.clone()).await;
+    backfill.request(WALLET_NAME, NETWORK, None).await.unwrap();
+
+    // 第二批（3..=5）读到区块 4 时失败：第一批已提交，游标停在 3
+    let report = backfill.run_once(&CancellationToken::new()).await;
+    assert_eq!((report.batches, report.errors, report.completed), (1, 1, 0));
+    let cursor = &storage.backfill_cursors(WALLET_NAME).await.unwrap()[0];
+    assert_eq!(cursor.next_block, 3);
+    assert!(cursor.last_error.as_deref().unwrap().contains("connection reset"));
+
+    // 已取消的运行什么也不做
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let report = backfill.run_once(&cancel).await;
+    assert!(report.cancelled);
+    assert_eq!(report.batches, 0);
+
+    let report = backfill.run_once(&CancellationToken::new()).await;
+    assert_eq!((report.batches, report.completed, report.errors), (2, 1, 0));
+    let cursor = &storage.backfill_cursors(WALLET_NAME).await.unwrap()[0];
+    assert_eq!((cursor.next_block, cursor.entries, cursor.last_error.as_deref()), (9, 8, None));
+    assert_eq!(storage.ledger_entries(WALLET_NAME, NETWORK).await.unwrap().len(), 8);
+    assert_eq!(records(&storage).await.len(), 7);
+    assert_ledger_matches_chain(&storage, &chain).await;
+
+    // 同一范围再请求一次：游标已完成，不再读取
+    let cursor = backfill.request(WALLET_NAME, NETWORK, Some(0)).await.unwrap();
+    assert_eq!(cursor.status, SCAN_COMPLETE);
+    assert_eq!(backfill.run_once(&CancellationToken::new()).await.batches, 0);
+}
+
+#[tokio::test]
+async fn test_non_custodial_wallet_gets_ledger_without_records() {
+    let chain = mixed_history();
+    let storage = Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap());
+    register_networks(&storage).await;
+    let backfill = HistoryBackfill::new(BackfillConfig::default(), storage.clone(), Arc::new(RpcHistory::new(chain.clone())));
+    backfill.request(WALLET_NAME, NETWORK, None).await.unwrap();
+
+    // 没有 wallets 行可关联：只写账目，不写 transactions
+    let report = backfill.run_once(&CancellationToken::new()).await;
+    assert_eq!((report.entries, report.completed, report.errors), (8, 1, 0));
+    assert_ledger_matches_chain(&storage, &chain).await;
+    let (_, total) = storage.query_transactions(&TransactionFilter::default(), 0, 1).await.unwrap();
+    assert_eq!(total, 0);
+}
+
+#[tokio::test]
+async fn test_rewind_and_extension_only_add_new_history() {
+    let chain = mixed_history();
+    let (storage, backfill) = setup(chain.clone()).await;
+
+    // 先只回填 5..=8（代币转出 2 条、自转、入账）
+    backfill.request(WALLET_NAME, NETWORK, Some(5)).await.unwrap();
+    assert_eq!(backfill.run_once(&CancellationToken::new()).await.entries, 4);
+
+    // 回退到创世块：5..=8 重读但不重复写入
+    let cursor = backfill.request(WALLET_NAME, NETWORK, None).await.unwrap();
+    assert_eq!((cursor.from_block, cursor.next_block), (0, 0));
+    let report = backfill.run_once(&CancellationToken::new()).await;
+    assert_eq!((report.batches, report.entries), (3, 4));
+    assert_eq!(storage.ledger_entries(WALLET_NAME, NETWORK).await.unwrap().len(), 8);
+    assert_eq!(records(&storage).await.len(), 7);
+
+    // 链继续出块后再请求：只读新区块
+    chain.mine(&[Op::Send { from: other(), to: wallet(), value: ETH, success: true }]);
+    let cursor = backfill.request(WALLET_NAME, NETWORK, None).await.unwrap();
+    assert_eq!((cursor.next_block, cursor.target_block, cursor.status.as_str()), (9, 9, "running"));
+    let report = backfill.run_once(&CancellationToken::new()).await;
+    assert_eq!((report.batches, report.entries), (1, 1));
+    assert_eq!(records(&storage).await.len(), 8);
+    assert_ledger_matches_chain(&storage, &chain).await;
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_backfill_endpoints() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    let chain = mixed_history();
+    let source = Arc::new(RpcHistory::new(chain));
+    let backfill = HistoryBackfill::new(BackfillConfig::default(), server.storage.clone(), source);
+    let server = server.with_backfill(backfill);
+    register_wallet(&server.storage).await;
+    let backfill = server.backfill.clone();
+    let server_storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    app.post("/api/wallets/imported/backfill?network=eth").await.assert_status_unauthorized();
+    app.post("/api/wallets/imported/backfill").add_header("X-API-KEY", API_KEY).await.assert_status_bad_request();
+    let res = app.post("/api/wallets/imported/backfill?network=polygon").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_not_found();
+    assert_eq!(res.json::<Value>()["error"]["code"], "NETWORK_NOT_REGISTERED");
+
+    let res = app.post("/api/wallets/imported/backfill?network=eth&from_block=2");
+    let res = res.add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["scheduled"], false, "jobs are only registered by `start`");
+    assert_eq!((body["cursor"]["from_block"].as_i64(), body["cursor"]["target_block"].as_i64()), (Some(2), Some(8)));
+
+    backfill.run_once(&CancellationToken::new()).await;
+    let res = app.get("/api/wallets/imported/backfill/status").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["cursors"][0]["status"], SCAN_COMPLETE);
+    assert_eq!(body["cursors"][0]["entries"], 7);
+    let balances = body["ledger"][0]["balances"].as_array().unwrap();
+    assert_eq!(body["ledger"][0]["network"], NETWORK);
+    assert_eq!(balances.len(), 2);
+
+    // 区块 2 之前的入账不在范围内
+    let transactions = records(&server_storage).await;
+    assert_eq!(transactions.len(), 6);
+    assert!(transactions.iter().all(|t| t.tx_hash != format!("{:?}", H256::from_low_u64_be(1))));
+}
@@ -0,0 +1,222 @@
+//! 余额快照任务与 `GET /api/wallets/:name/balance_history` 集成测试
+//!
+//! 快照使用 MockProvider 支撑的 EthereumClient（响应按 LIFO 弹出）。
+
+mod util;
+
+use axum_test::TestServer;
+use chrono::{TimeZone, Utc};
+use ethers::providers::{MockProvider, Provider};
+use ethers::types::{U256, U64};
+use serde_json::Value;
+use std::sync::Arc;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::api::types::BalanceHistoryResponse;
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+use defi_hot_wallet::core::config::WalletConfig;
+use defi_hot_wallet::storage::{BalanceSnapshot, NATIVE_TOKEN};
+
+const TOKEN: &str = "balance-history-token";
+const OTHER_TOKEN: &str = "balance-history-other-token";
+const ADDRESS: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+
+struct Harness {
+    server: WalletServer,
+    mock: MockProvider,
+    user_id: String,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    build_with(util::memory_config()).await
+}
+
+async fn build_with(config: WalletConfig) -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    let client = EthereumClient::new_with_provider_and_chain(provider, "eth", 1);
+    let server = util::test_server(dir.path(), config, None)
+        .await
+        .with_chain_clients(ClientRegistry::new().with_client("eth", Arc::new(client)));
+
+    let user_id = util::sign_in(&server, "charts@example.com", TOKEN).await;
+    util::sign_in(&server, "other@example.com", OTHER_TOKEN).await;
+
+    // 两个wallet共用一个address：每轮只应查询一次
+    for name in ["savings", "savings_alias"] {
+        server.user_db.link_wallet(&user_id, name, ADDRESS, None).await.unwrap();
+    }
+
+    Harness { server, mock, user_id, _dir: dir }
+}
+
+/// 为一轮快照准备响应：先 block number，后 balance（LIFO 逆序压入）
+fn push_cycle(mock: &MockProvider, wei: u64, block: u64) {
+    mock.push(U256::from(wei)).unwrap();
+    mock.push(U64::from(block)).unwrap();
+}
+
+async fn wallet_id(h: &Harness, name: &str) -> String {
+    h.server.user_db.find_user_wallet(&h.user_id, name).await.unwrap().unwrap().0.to_string()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_snapshot_cycle_dedupes_queries_and_skips_unchanged() {
+    let h = build().await;
+    let snapshotter = h.server.balance_snapshotter();
+
+    push_cycle(&h.mock, 1_500_000_000_000_000_000, 100);
+    let report = snapshotter.run_once().await;
+    assert_eq!(report.failed, 0, "{:?}", report);
+    assert_eq!(report.recorded, 2);
+
+    // 余额未变：不写新行
+    push_cycle(&h.mock, 1_500_000_000_000_000_000, 101);
+    let report = snapshotter.run_once().await;
+    assert_eq!(report.failed, 0, "{:?}", report);
+    assert_eq!((report.recorded, report.unchanged), (0, 2));
+
+    let app = TestServer::new(h.server.clone().create_router().await).unwrap();
+    let res = app
+        .get("/api/wallets/savings/balance_history?network=eth")
+        .add_header("Authorization", format!("Bearer {}", TOKEN))
+        .await;
+    res.assert_status_ok();
+    let body: BalanceHistoryResponse = res.json();
+    assert_eq!(body.token, NATIVE_TOKEN);
+    // 只有一行快照，落在最后一个小时桶
+    let observed: Vec<_> = body.points.iter().filter(|p| !p.interpolated).collect();
+    assert_eq!(observed.len(), 1);
+    assert_eq!(observed[0].balance, "1.500000000000000000");
+    assert_eq!(observed[0].block_number, Some(100));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_schedule_and_networks_come_from_config() {
+    // hot_wallet 启动时按 [balance_snapshots] 段读取
+    let doc: toml::Value =
+        toml::from_str("[balance_snapshots]\ninterval_secs = 900\nnetworks = [\"polygon\"]\n").unwrap();
+    let mut config = util::memory_config();
+    config.balance_snapshots = doc["balance_snapshots"].clone().try_into().unwrap();
+    assert_eq!(config.balance_snapshots.per_network_concurrency, 4);
+    let h = build_with(config).await;
+    let snapshotter = h.server.balance_snapshotter();
+    assert_eq!(snapshotter.interval(), std::time::Duration::from_secs(900));
+
+    // eth 不在列表中：不查询、不记录
+    push_cycle(&h.mock, 1_000_000_000_000_000_000, 100);
+    let report = snapshotter.run_once().await;
+    assert_eq!((report.recorded, report.failed), (0, 0), "{:?}", report);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_snapshot_respects_maintenance_and_circuit_breaker() {
+    let h = build().await;
+    let snapshotter = h.server.balance_snapshotter();
+
+    h.server.maintenance.enable("upgrade");
+    let report = snapshotter.run_once().await;
+    assert!(report.skipped);
+    assert_eq!(report.recorded, 0);
+    h.server.maintenance.disable();
+
+    for _ in 0..5 {
+        h.server.circuit_breaker.record_failure("eth");
+    }
+    let report = snapshotter.run_once().await;
+    assert_eq!(report.skipped_networks, vec!["eth".to_string()]);
+    assert_eq!(report.recorded, 0);
+
+    // RPC 失败只计数，不影响后续轮次
+    h.server.circuit_breaker.record_success("eth");
+    let report = snapshotter.run_once().await;
+    assert!(report.failed > 0);
+    push_cycle(&h.mock, 7, 5);
+    assert_eq!(snapshotter.run_once().await.recorded, 2);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_seeded_series_interpolates_gaps() {
+    let h = build().await;
+    let id = wallet_id(&h, "savings").await;
+    let base = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap().timestamp();
+    for (balance, offset) in [("1", -1_800), ("2", 3_600 + 60), ("3", 3 * 3_600 + 60)] {
+        let snapshot = BalanceSnapshot {
+            wallet_id: id.clone(),
+            network: "eth".into(),
+            token: NATIVE_TOKEN.into(),
+            balance: balance.into(),
+            block_number: None,
+            captured_at: base + offset,
+        };
+        assert!(h.server.storage.record_balance_snapshot(&snapshot).await.unwrap());
+    }
+
+    let app = TestServer::new(h.server.clone().create_router().await).unwrap();
+    let body: Value = app
+        .get("/api/wallets/savings/balance_history?network=eth&resolution=hour&from=2024-03-01T00:00:00Z&to=2024-03-01T05:00:00Z")
+        .add_header("Authorization", format!("Bearer {}", TOKEN))
+        .await
+        .json();
+    let got: Vec<(String, String, bool)> = body["points"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| {
+            (
+                p["timestamp"].as_str().unwrap().to_string(),
+                p["balance"].as_str().unwrap().to_string(),
+                p["interpolated"].as_bool().unwrap(),
+            )
+        })
+        .collect();
+    let expected = [("00", "1", true), ("01", "2", false), ("02", "2", true), ("03", "3", false), ("04", "3", true)];
+    assert_eq!(got.len(), expected.len());
+    for ((ts, balance, interpolated), (hour, want_balance, want_interp)) in got.iter().zip(expected) {
+        assert!(ts.starts_with(&format!("2024-03-01T{}:00:00", hour)), "{}", ts);
+        assert_eq!((balance.as_str(), *interpolated), (want_balance, want_interp));
+    }
+
+    // 另一个wallet没有快照
+    let body: Value = app
+        .get("/api/wallets/savings_alias/balance_history?network=eth&resolution=day")
+        .add_header("Authorization", format!("Bearer {}", TOKEN))
+        .await
+        .json();
+    assert!(body["points"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_balance_history_rejects_bad_requests() {
+    let h = build().await;
+    let app = TestServer::new(h.server.clone().create_router().await).unwrap();
+
+    let res = app
+        .get("/api/wallets/savings/balance_history?network=eth&resolution=hour&from=2024-01-01T00:00:00Z&to=2024-03-01T00:00:00Z")
+        .add_header("Authorization", format!("Bearer {}", TOKEN))
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_RANGE");
+
+    let res = app
+        .get("/api/wallets/savings/balance_history?network=eth&from=2024-03-01T00:00:00Z&to=2024-01-01T00:00:00Z")
+        .add_header("Authorization", format!("Bearer {}", TOKEN))
+        .await;
+    res.assert_status_bad_request();
+
+    let res = app
+        .get("/api/wallets/savings/balance_history?network=eth")
+        .add_header("Authorization", format!("Bearer {}", OTHER_TOKEN))
+        .await;
+    res.assert_status_forbidden();
+
+    let res = app.get("/api/wallets/savings/balance_history?network=eth").await;
+    res.assert_status_unauthorized();
+}
@@ -0,0 +1,228 @@
+//! 余额变化订阅：`/api/wallets/:name/subscriptions` CRUD 与 `observe_balance`
+//! 评估（阈值、防抖合并、重启幂等、删除wallet后自动停用）
+//!
+//! 存储落在临时文件上，用同一文件新开的 `WalletStorage` 模拟扫描器重启。
+
+mod util;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use chrono::Duration;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::clock::FixedClock;
+use defi_hot_wallet::core::ids::SequentialIds;
+use defi_hot_wallet::storage::{journal_events, BalanceObservation, JournalEvent, WalletStorage, NATIVE_TOKEN};
+
+const TOKEN: &str = "balance-subscriptions-token";
+const WALLET: &str = "vault";
+const ADDRESS: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+const NOW: i64 = 1_700_000_000;
+const DEBOUNCE: u64 = 60;
+
+struct Harness {
+    app: TestServer,
+    server: WalletServer,
+    clock: Arc<FixedClock>,
+    db_url: String,
+    wallet_id: i64,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let config = util::file_config(dir.path());
+    let db_url = config.storage.database_url.clone();
+    let clock = Arc::new(FixedClock::at(NOW));
+    let server =
+        util::test_server_with_sources(dir.path(), config, None, clock.clone(), Arc::new(SequentialIds::new())).await;
+
+    let user_id = util::sign_in(&server, "subscriber@example.com", TOKEN).await;
+    server.user_db.link_wallet(&user_id, WALLET, ADDRESS, None).await.unwrap();
+    let (wallet_id, _) = server.user_db.find_user_wallet(&user_id, WALLET).await.unwrap().unwrap();
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { app, server, clock, db_url, wallet_id, _dir: dir }
+}
+
+impl Harness {
+    async fn subscribe(&self, min_delta: &str) -> String {
+        let res = self
+            .app
+            .post(&format!("/api/wallets/{}/subscriptions", WALLET))
+            .add_header("Authorization", format!("Bearer {}", TOKEN))
+            .json(&json!({ "network": "eth", "min_delta": min_delta, "webhook_target": "ops-hook" }))
+            .await;
+        res.assert_status_ok();
+        let body: Value = res.json();
+        assert_eq!(body["active"], true);
+        body["id"].as_str().unwrap().to_string()
+    }
+
+    async fn observe(&self, storage: &WalletStorage, balance: u128, tx_hash: Option<&str>, debounce: u64) -> usize {
+        let obs = BalanceObservation { wallet_id: self.wallet_id, network: "eth", token: NATIVE_TOKEN, balance, tx_hash };
+        storage.observe_balance(&obs, debounce).await.unwrap()
+    }
+
+    fn advance(&self, secs: i64) {
+        self.clock.advance(Duration::seconds(secs));
+    }
+
+    /// 与扫描器重启后一样：同一数据库文件上的新 `WalletStorage`
+    async fn restarted_storage(&self) -> WalletStorage {
+        WalletStorage::new_with_url(&self.db_url).await.unwrap().with_clock(self.clock.clone())
+    }
+}
+
+async fn balance_events(storage: &WalletStorage) -> Vec<JournalEvent> {
+    let events = storage.journal_events(0, 1000).await.unwrap();
+    events.into_iter().filter(|e| e.event_type == journal_events::BALANCE_CHANGED).collect()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_changes_below_min_delta_are_not_reported() {
+    let h = build().await;
+    let storage = h.server.storage.clone();
+    let id = h.subscribe("1000").await;
+
+    // 第一次观测只建立基线
+    assert_eq!(h.observe(&storage, 10_000, None, 0).await, 0);
+    assert_eq!(h.observe(&storage, 10_999, None, 0).await, 0);
+    assert_eq!(h.observe(&storage, 9_001, None, 0).await, 0);
+    assert_eq!(h.observe(&storage, 11_000, Some("0xabc"), 0).await, 1);
+
+    let events = balance_events(&storage).await;
+    assert_eq!(events.len(), 1);
+    let payload = &events[0].payload;
+    assert_eq!(payload["subscription_id"], id.as_str());
+    assert_eq!(payload["webhook_target"], "ops-hook");
+    assert_eq!(payload["wallet_name"], WALLET);
+    assert_eq!((payload["old_balance"].as_str(), payload["new_balance"].as_str()), (Some("10000"), Some("11000")));
+    assert_eq!(payload["delta"], "1000");
+    assert_eq!(payload["tx_hash"], "0xabc");
+
+    // 阈值相对上次通知的余额：回落 1000 也要报，delta 为负
+    assert_eq!(h.observe(&storage, 10_500, None, 0).await, 0);
+    assert_eq!(h.observe(&storage, 10_000, None, 0).await, 1);
+    let events = balance_events(&storage).await;
+    assert_eq!(events[1].payload["delta"], "-1000");
+    assert_eq!(events[1].payload["tx_hash"], Value::Null);
+
+    // 参数校验
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/subscriptions", WALLET))
+        .add_header("Authorization", format!("Bearer {}", TOKEN))
+        .json(&json!({ "network": "eth", "min_delta": "0.5", "webhook_target": "ops-hook" }))
+        .await;
+    res.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_MIN_DELTA");
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/subscriptions", WALLET))
+        .add_header("Authorization", format!("Bearer {}", TOKEN))
+        .json(&json!({ "network": "btc", "token": ADDRESS, "min_delta": "1", "webhook_target": "ops-hook" }))
+        .await;
+    res.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(res.json::<Value>()["error"]["code"], "UNSUPPORTED_NETWORK");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_rapid_deposits_are_debounced_into_one_event() {
+    let h = build().await;
+    let storage = h.server.storage.clone();
+    h.subscribe("100").await;
+    h.observe(&storage, 1_000, None, DEBOUNCE).await;
+
+    // 一分钟内三笔入账
+    let first_change_at = h.server.storage.clock().now().timestamp();
+    assert_eq!(h.observe(&storage, 1_150, Some("0x01"), DEBOUNCE).await, 0);
+    h.advance(10);
+    assert_eq!(h.observe(&storage, 1_300, Some("0x02"), DEBOUNCE).await, 0);
+    h.advance(20);
+    assert_eq!(h.observe(&storage, 1_450, Some("0x03"), DEBOUNCE).await, 0);
+    assert!(balance_events(&storage).await.is_empty());
+
+    // 窗口结束后的下一次观测（余额未变）发出合并事件
+    h.advance(31);
+    assert_eq!(h.observe(&storage, 1_450, None, DEBOUNCE).await, 1);
+    assert_eq!(h.observe(&storage, 1_450, None, DEBOUNCE).await, 0);
+
+    let events = balance_events(&storage).await;
+    assert_eq!(events.len(), 1);
+    let payload = &events[0].payload;
+    assert_eq!((payload["old_balance"].as_str(), payload["new_balance"].as_str()), (Some("1000"), Some("1450")));
+    assert_eq!(payload["delta"], "450");
+    assert_eq!(payload["tx_hash"], "0x03");
+    assert_eq!(payload["first_change_at"], first_change_at);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_evaluation_is_idempotent_across_restart() {
+    let h = build().await;
+    h.subscribe("100").await;
+    let first = h.server.storage.clone();
+    h.observe(&first, 5_000, None, DEBOUNCE).await;
+    assert_eq!(h.observe(&first, 6_000, Some("0x01"), 0).await, 1);
+
+    // 重启后重放同一余额：已通知过，不再重复
+    let second = h.restarted_storage().await;
+    assert_eq!(h.observe(&second, 6_000, Some("0x01"), 0).await, 0);
+
+    // 打开的防抖窗口也跨重启保留
+    assert_eq!(h.observe(&second, 7_000, Some("0x02"), DEBOUNCE).await, 0);
+    drop(second);
+    h.advance(DEBOUNCE as i64);
+    let third = h.restarted_storage().await;
+    assert_eq!(h.observe(&third, 7_000, None, DEBOUNCE).await, 1);
+    assert_eq!(h.observe(&third, 7_000, None, DEBOUNCE).await, 0);
+
+    let events = balance_events(&third).await;
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].payload["old_balance"], "6000");
+    assert_eq!(events[1].payload["tx_hash"], "0x02");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_wallet_deletion_deactivates_subscriptions() {
+    let h = build().await;
+    let storage = h.server.storage.clone();
+    let id = h.subscribe("1").await;
+    h.observe(&storage, 100, None, 0).await;
+
+    let res = h
+        .app
+        .patch(&format!("/api/wallets/{}/subscriptions/{}", WALLET, id))
+        .add_header("Authorization", format!("Bearer {}", TOKEN))
+        .json(&json!({ "min_delta": "50" }))
+        .await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["min_delta"], "50");
+
+    let res = h
+        .app
+        .delete(&format!("/api/wallets/{}", WALLET))
+        .add_header("Authorization", format!("Bearer {}", TOKEN))
+        .await;
+    res.assert_status_ok();
+
+    let subs = storage.balance_subscriptions_for(h.wallet_id).await.unwrap();
+    assert_eq!(subs.len(), 1);
+    assert!(!subs[0].active);
+    assert_eq!(h.observe(&storage, 1_000, None, 0).await, 0);
+    assert!(balance_events(&storage).await.is_empty());
+
+    // wallet不存在了，API 也不再暴露它的订阅
+    let res = h
+        .app
+        .get(&format!("/api/wallets/{}/subscriptions", WALLET))
+        .add_header("Authorization", format!("Bearer {}", TOKEN))
+        .await;
+    assert_ne!(res.status_code(), StatusCode::OK);
+}
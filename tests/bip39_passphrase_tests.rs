@@ -0,0 +1,113 @@
+//! BIP39 passphrase：恢复时参与 seed 派生，错误的 passphrase 也能恢复但得到另一组address；
+//! 响应返回第一个派生address供核对，派生的密钥按 password 加密保存；备份注明需要 passphrase
+
+mod util;
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+
+
+const API_KEY: &str = "bip39-passphrase-admin-key";
+const OWNER: &str = "bip39-passphrase-owner-token";
+const PASSWORD: &str = "Rest0re!Passphrase#2024";
+const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+async fn build() -> (TestServer, tempfile::TempDir) {
+    util::set_test_env();
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY)).await;
+    util::sign_in(&server, "passphrase@example.com", OWNER).await;
+    (TestServer::new(server.create_router().await).unwrap(), dir)
+}
+
+async fn restore(app: &TestServer, name: &str, passphrase: Option<&str>) -> Value {
+    let mut body = json!({ "name": name, "seed_phrase": MNEMONIC });
+    if let Some(passphrase) = passphrase {
+        body["passphrase"] = json!(passphrase);
+        body["password"] = json!(PASSWORD);
+    }
+    let res = app.post("/api/wallets/restore").add_header("X-API-KEY", API_KEY).json(&body).await;
+    res.assert_status_ok();
+    res.json()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_restore_returns_first_address_for_the_passphrase() {
+    let (app, _dir) = build().await;
+
+    let plain = restore(&app, "plain", None).await;
+    let plain_address = plain["address"].as_str().unwrap().to_string();
+    assert!(plain_address.starts_with("0x") && plain_address.len() == 42, "{}", plain_address);
+    assert!(plain.get("warning").is_none());
+    // 空 passphrase 即标准 BIP39 seed
+    assert_eq!(restore(&app, "plain_empty", Some("")).await["address"], plain_address.as_str());
+
+    let protected = restore(&app, "protected", Some("TREZOR")).await;
+    let address = protected["address"].as_str().unwrap();
+    assert_ne!(address, plain_address);
+    assert!(protected["warning"].as_str().unwrap().contains("passphrase"));
+    assert_eq!(restore(&app, "protected_again", Some("TREZOR")).await["address"], address);
+
+    // passphrase 输错（大小写不同）照样恢复，只是地址不同
+    let wrong = restore(&app, "wrong", Some("trezor")).await;
+    assert_ne!(wrong["address"], address);
+    assert_ne!(wrong["address"], plain_address.as_str());
+
+    // 校验和错误的mnemonic仍然拒绝
+    let bad = MNEMONIC.replace("about", "abandon");
+    let res = app
+        .post("/api/wallets/restore")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "name": "bad", "seed_phrase": bad, "passphrase": "TREZOR", "password": PASSWORD }))
+        .await;
+    res.assert_status_bad_request();
+
+    // passphrase 派生的密钥必须能保存
+    let res = app
+        .post("/api/wallets/restore")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "name": "keyless", "seed_phrase": MNEMONIC, "passphrase": "TREZOR" }))
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "PASSWORD_REQUIRED");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_backup_marks_passphrase_required() {
+    let (app, _dir) = build().await;
+    restore(&app, "with_pass", Some("correct horse")).await;
+    restore(&app, "without_pass", None).await;
+
+    let backup: Value = app.get("/api/wallets/with_pass/backup").add_header("X-API-KEY", API_KEY).await.json();
+    assert_eq!(backup["passphrase_required"], true);
+    assert!(backup["warning"].as_str().unwrap().contains("passphrase"));
+
+    let backup: Value = app.get("/api/wallets/without_pass/backup").add_header("X-API-KEY", API_KEY).await.json();
+    assert_eq!(backup["passphrase_required"], false);
+    assert!(backup.get("warning").is_none());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_non_custodial_create_rejects_passphrase() {
+    let (app, _dir) = build().await;
+    let body = json!({
+        "name": "client_side",
+        "wallet_address": "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "passphrase": "TREZOR",
+    });
+    let res = app.post("/api/wallets").add_header("Authorization", format!("Bearer {}", OWNER)).json(&body).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_INPUT");
+
+    // 空 passphrase 等同于未提供
+    let body = json!({
+        "name": "client_side",
+        "wallet_address": "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "passphrase": "",
+    });
+    let res = app.post("/api/wallets").add_header("Authorization", format!("Bearer {}", OWNER)).json(&body).await;
+    res.assert_status_ok();
+}
@@ -0,0 +1,351 @@
+//! 按区块跟踪确认：交易在包含它的区块被发现、确认数按高度推进而不重复取收据、
+//! 深度 2 的重组后重新处理受影响的交易、内存中的待确认集合有上限，
+//! 以及同一场景下与逐笔轮询的最终状态一致
+
+use async_trait::async_trait;
+use ethers::signers::LocalWallet;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionReceipt, TransactionRequest, H256, U256, U64};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::core::config::{BlockTrackingConfig, FeeTrackingConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::{BroadcastChain, ChainBlock, SigningIntentLog};
+use defi_hot_wallet::ops::block_tracking::{BlockTracker, BlockTrackingReport};
+use defi_hot_wallet::ops::fee_tracking::ConfirmationPoller;
+use defi_hot_wallet::storage::WalletStorage;
+
+const NETWORK: &str = "eth";
+const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const GWEI: u64 = 1_000_000_000;
+
+/// 按脚本出块的链：`mine` 追加区块并生成其中交易的收据，`reorg` 丢弃最上面的区块
+#[derive(Default)]
+struct ScriptedChain {
+    nonce: Mutex<u64>,
+    /// 下标即高度
+    blocks: Mutex<Vec<ChainBlock>>,
+    receipts: Mutex<HashMap<H256, TransactionReceipt>>,
+    receipt_calls: Mutex<HashMap<H256, usize>>,
+    minted: Mutex<u64>,
+}
+
+impl ScriptedChain {
+    fn new() -> Arc<Self> {
+        let chain = Self::default();
+        chain.mine(&[]);
+        Arc::new(chain)
+    }
+
+    /// `(hash, success)`；返回区块高度
+    fn mine(&self, txs: &[(&str, bool)]) -> u64 {
+        let mut blocks = self.blocks.lock().unwrap();
+        let number = blocks.len() as u64;
+        let mut minted = self.minted.lock().unwrap();
+        *minted += 1;
+        // 每个区块的 hash 都不同，重组后同一高度也不会重复
+        let hash = H256::from_low_u64_be(*minted);
+        let parent_hash = blocks.last().map(|b| b.hash).unwrap_or_default();
+        let mut receipts = self.receipts.lock().unwrap();
+        let mut transactions = Vec::new();
+        for (tx, success) in txs {
+            let tx: H256 = tx.parse().unwrap();
+            transactions.push(tx);
+            let receipt = TransactionReceipt {
+                transaction_hash: tx,
+                block_number: Some(U64::from(number)),
+                block_hash: Some(hash),
+                gas_used: Some(U256::from(21_000u64)),
+                effective_gas_price: Some(U256::from(2 * GWEI)),
+                status: Some(U64::from(*success as u64)),
+                ..Default::default()
+            };
+            receipts.insert(tx, receipt);
+        }
+        blocks.push(ChainBlock { number, hash, parent_hash, transactions });
+        number
+    }
+
+    fn reorg(&self, depth: usize) {
+        let mut blocks = self.blocks.lock().unwrap();
+        let keep = blocks.len() - depth;
+        let mut receipts = self.receipts.lock().unwrap();
+        for block in blocks.drain(keep..) {
+            for tx in block.transactions {
+                receipts.remove(&tx);
+            }
+        }
+    }
+
+    fn receipt_calls(&self, hash: &str) -> usize {
+        self.receipt_calls.lock().unwrap().get(&hash.parse().unwrap()).copied().unwrap_or(0)
+    }
+}
+
+#[async_trait]
+impl BroadcastChain for ScriptedChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3 * GWEI);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        *self.receipt_calls.lock().unwrap().entry(tx_hash).or_default() += 1;
+        Ok(self.receipts.lock().unwrap().get(&tx_hash).cloned())
+    }
+
+    async fn block_number(&self, _network: &str) -> Result<u64, WalletError> {
+        Ok(self.blocks.lock().unwrap().len() as u64 - 1)
+    }
+
+    async fn block(&self, _network: &str, number: u64) -> Result<Option<ChainBlock>, WalletError> {
+        Ok(self.blocks.lock().unwrap().get(number as usize).cloned())
+    }
+}
+
+struct Harness {
+    storage: Arc<WalletStorage>,
+    chain: Arc<ScriptedChain>,
+    log: SigningIntentLog,
+    signer: LocalWallet,
+    _dir: tempfile::TempDir,
+}
+
+async fn harness() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display());
+    // 落库wallet写审计记录，其 MAC 需要 WALLET_ENC_KEY
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let storage = Arc::new(WalletStorage::new_with_url(&url).await.unwrap());
+    storage.store_wallet("hot", b"blob", false).await.unwrap();
+    let chain = ScriptedChain::new();
+    let log = SigningIntentLog::new(storage.clone(), chain.clone());
+    Harness { storage, chain, log, signer: KEY.parse().unwrap(), _dir: dir }
+}
+
+fn block_tracking(max_pending: usize) -> FeeTrackingConfig {
+    FeeTrackingConfig {
+        block_tracking: BlockTrackingConfig {
+            networks: vec![NETWORK.to_string()],
+            max_pending,
+            // 每轮都与数据库同步
+            reconcile_interval_secs: 0,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+impl Harness {
+    /// 金额各不相同，避免触发重复发送检测
+    async fn send(&self, value: u64) -> String {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to("0x000000000000000000000000000000000000dEaD".parse::<Address>().unwrap())
+            .value(value)
+            .into();
+        self.log.send("hot", NETWORK, &self.signer, tx).await.unwrap()
+    }
+
+    async fn status(&self, hash: &str) -> String {
+        self.storage.transaction_by_hash(hash).await.unwrap().unwrap().status
+    }
+
+    fn tracker(&self, config: FeeTrackingConfig, required_confirmations: u64) -> BlockTracker {
+        BlockTracker::new(NETWORK, config, required_confirmations, self.storage.clone(), self.chain.clone())
+    }
+}
+
+#[tokio::test]
+async fn test_inclusion_is_detected_in_the_containing_block() {
+    let h = harness().await;
+    let tracker = h.tracker(block_tracking(100), 3);
+    let tx = h.send(1).await;
+
+    // 首次发现时查一次收据：尚未出块
+    let report = tracker.run_once().await;
+    assert_eq!((report.receipts_fetched, report.included), (1, 0));
+    assert_eq!(tracker.tracked().await, 1);
+
+    // 不含我们交易的区块不取收据
+    let foreign = format!("{:?}", H256::repeat_byte(0xee));
+    h.chain.mine(&[(&foreign, true)]);
+    assert_eq!(tracker.run_once().await, BlockTrackingReport { blocks: 1, ..Default::default() });
+    assert_eq!(h.chain.receipt_calls(&foreign), 0);
+
+    h.chain.mine(&[(&tx, true)]);
+    let report = tracker.run_once().await;
+    assert_eq!((report.blocks, report.included, report.receipts_fetched), (1, 1, 1));
+    assert_eq!(tracker.confirmations(&tx).await, Some(1));
+    assert_eq!(h.chain.receipt_calls(&tx), 2);
+    // 确认数不足，状态不动
+    assert_eq!(h.status(&tx).await, "pending");
+}
+
+#[tokio::test]
+async fn test_confirmations_advance_by_height_without_refetching_receipts() {
+    let h = harness().await;
+    let tracker = h.tracker(block_tracking(100), 6);
+    let mut sends = Vec::new();
+    for value in 1..=5 {
+        sends.push(h.send(value).await);
+    }
+    tracker.run_once().await;
+
+    let mined: Vec<(&str, bool)> = sends.iter().map(|s| (s.as_str(), true)).collect();
+    h.chain.mine(&mined);
+    let report = tracker.run_once().await;
+    assert_eq!((report.included, report.receipts_fetched), (5, 5));
+
+    for depth in 2..6 {
+        h.chain.mine(&[]);
+        let report = tracker.run_once().await;
+        assert_eq!(report, BlockTrackingReport { blocks: 1, ..Default::default() });
+        for send in &sends {
+            assert_eq!(tracker.confirmations(send).await, Some(depth));
+            assert_eq!(h.status(send).await, "pending");
+        }
+    }
+
+    h.chain.mine(&[]);
+    let report = tracker.run_once().await;
+    assert_eq!((report.confirmed, report.receipts_fetched), (5, 0));
+    assert_eq!(tracker.tracked().await, 0);
+    for send in &sends {
+        // 首次查询 + 所在区块各一次
+        assert_eq!(h.chain.receipt_calls(send), 2);
+        assert_eq!(h.status(send).await, "confirmed");
+        let fee = h.storage.fee_record(NETWORK, send).await.unwrap().unwrap();
+        assert_eq!((fee.gas_used, fee.effective_gas_price), (Some(21_000), Some((2 * GWEI) as i64)));
+    }
+}
+
+#[tokio::test]
+async fn test_reorg_of_depth_two_reprocesses_affected_transactions() {
+    let h = harness().await;
+    let tracker = h.tracker(block_tracking(100), 3);
+    let a = h.send(1).await;
+    let b = h.send(2).await;
+    tracker.run_once().await;
+
+    h.chain.mine(&[(&a, true)]);
+    h.chain.mine(&[(&b, true)]);
+    let report = tracker.run_once().await;
+    assert_eq!(report.included, 2);
+    assert_eq!(tracker.confirmations(&a).await, Some(2));
+    assert_eq!(tracker.confirmations(&b).await, Some(1));
+
+    // 替代链：b 先上链，a 在新链上执行失败
+    h.chain.reorg(2);
+    h.chain.mine(&[]);
+    h.chain.mine(&[(&b, true)]);
+    h.chain.mine(&[(&a, false)]);
+    let report = tracker.run_once().await;
+    assert_eq!((report.reorged, report.included, report.receipts_fetched), (2, 2, 2));
+    assert_eq!(tracker.confirmations(&b).await, Some(2));
+    assert_eq!(tracker.confirmations(&a).await, Some(1));
+    assert_eq!((h.chain.receipt_calls(&a), h.chain.receipt_calls(&b)), (3, 3));
+    assert_eq!(h.status(&a).await, "pending");
+
+    h.chain.mine(&[]);
+    assert_eq!(tracker.run_once().await.confirmed, 1);
+    assert_eq!(h.status(&b).await, "confirmed");
+    h.chain.mine(&[]);
+    assert_eq!(tracker.run_once().await.reverted, 1);
+    assert_eq!(h.status(&a).await, "failed");
+}
+
+#[tokio::test]
+async fn test_pending_set_is_bounded_and_refilled_from_storage() {
+    let h = harness().await;
+    let tracker = h.tracker(block_tracking(2), 1);
+    let sends = [h.send(1).await, h.send(2).await, h.send(3).await];
+
+    let report = tracker.run_once().await;
+    assert_eq!((report.receipts_fetched, tracker.tracked().await), (2, 2));
+    assert_eq!(h.chain.receipt_calls(&sends[2]), 0);
+
+    h.chain.mine(&[(&sends[0], true), (&sends[1], true), (&sends[2], true)]);
+    let report = tracker.run_once().await;
+    assert_eq!((report.included, report.confirmed), (2, 2));
+
+    // 已确认的移出后，下一次同步补入剩下的一笔；它已上链，查一次收据即可
+    let report = tracker.run_once().await;
+    assert_eq!((report.included, report.confirmed, report.receipts_fetched), (1, 1, 1));
+    assert_eq!(tracker.tracked().await, 0);
+    for send in &sends {
+        assert_eq!(h.status(send).await, "confirmed");
+    }
+}
+
+/// 同一脚本分别用逐笔轮询和区块跟踪跑一遍，返回各笔交易的最终状态与 gas used
+async fn run_scenario(block_mode: bool) -> Vec<(String, Option<i64>)> {
+    let h = harness().await;
+    let config = if block_mode { block_tracking(100) } else { FeeTrackingConfig::default() };
+    let poller = ConfirmationPoller::new(config.clone(), h.storage.clone(), h.chain.clone());
+    let tracker = h.tracker(config, 2);
+    let (poller, tracker) = (&poller, &tracker);
+    let cycle = move || async move {
+        let polled = poller.run_once().await;
+        if block_mode {
+            // 区块跟踪的network不再逐笔轮询
+            assert_eq!(polled.checked, 0);
+            tracker.run_once().await;
+        }
+    };
+
+    let mut sends = Vec::new();
+    for value in 1..=4 {
+        sends.push(h.send(value).await);
+    }
+    cycle().await;
+    h.chain.mine(&[(&sends[0], true), (&sends[1], false)]);
+    cycle().await;
+    h.chain.mine(&[]);
+    cycle().await;
+    h.chain.mine(&[(&sends[2], true)]);
+    cycle().await;
+    h.chain.reorg(1);
+    h.chain.mine(&[]);
+    h.chain.mine(&[(&sends[2], true)]);
+    cycle().await;
+    h.chain.mine(&[(&sends[3], true)]);
+    for _ in 0..3 {
+        h.chain.mine(&[]);
+        cycle().await;
+    }
+
+    let mut outcome = Vec::new();
+    for send in &sends {
+        let fee = h.storage.fee_record(NETWORK, send).await.unwrap().unwrap();
+        outcome.push((h.status(send).await, fee.gas_used));
+    }
+    outcome
+}
+
+#[tokio::test]
+async fn test_final_statuses_match_legacy_polling() {
+    let legacy = run_scenario(false).await;
+    let tracked = run_scenario(true).await;
+    assert_eq!(tracked, legacy);
+    let statuses: Vec<&str> = tracked.iter().map(|(status, _)| status.as_str()).collect();
+    assert_eq!(statuses, ["confirmed", "failed", "confirmed", "confirmed"]);
+    assert!(tracked.iter().all(|(_, gas_used)| *gas_used == Some(21_000)));
+}
@@ -4,13 +4,13 @@ use serde_json::json;
 use std::sync::Arc;
 use tracing::{info, warn};
 
-use defi_hot_wallet::api::types::{BridgeAssetsRequest, ErrorResponse};
+use defi_hot_wallet::api::types::{ApiError, BridgeAssetsRequest};
 use defi_hot_wallet::core::wallet_manager::WalletManager;
 
 pub async fn bridge_assets(
     State(wallet_manager): State<Arc<WalletManager>>,
     Json(request): Json<BridgeAssetsRequest>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     // If the wallet does not exist in the state, return a 404 error.
     // This is crucial for testing the 'wallet_not_found' scenario.
     if !wallet_manager
@@ -20,13 +20,7 @@ pub async fn bridge_assets(
         .iter()
         .any(|w| w.name == request.from_wallet)
     {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "Wallet not found".to_string(),
-                code: "WALLET_NOT_FOUND".to_string(),
-            }),
-        ));
+        return Err(ApiError::from_code(StatusCode::NOT_FOUND, "WALLET_NOT_FOUND", "Wallet not found"));
     }
 
     info!("Test bridge called: {} -> {}", request.from_chain, request.to_chain);
@@ -44,10 +38,7 @@ pub async fn bridge_assets(
         Ok(bridge_tx_id) => Ok(Json(json!({ "bridge_tx_id": bridge_tx_id }))),
         Err(e) => {
             warn!("bridge failed: {}", e);
-            Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse { error: e.to_string(), code: "BRIDGE_FAILED".to_string() }),
-            ))
+            Err(ApiError::from_code(StatusCode::BAD_REQUEST, "BRIDGE_FAILED", e.to_string()))
         }
     }
 }
@@ -0,0 +1,139 @@
+//! BridgeBackend 启动判定与路由工厂的集成测试
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{BridgeBackend, StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "bridge-backend-test-key-0123456789abcdef";
+
+fn config(backend: BridgeBackend) -> WalletConfig {
+    WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        bridge_backend: backend,
+        ..Default::default()
+    }
+}
+
+fn api_key() -> Option<SecretVec> {
+    Some(SecretVec::new(API_KEY.as_bytes().to_vec()))
+}
+
+#[tokio::test]
+async fn test_production_server_refuses_mock_backend() {
+    if cfg!(feature = "test-env") {
+        // test-env builds always permit mocks
+        return;
+    }
+    let res = WalletServer::new("127.0.0.1".to_string(), 0, config(BridgeBackend::Mock), None).await;
+    assert!(matches!(res, Err(WalletError::ConfigError(_))));
+
+    // 显式 --allow-insecure-mocks 时放行
+    let server = WalletServer::new_with_mock_policy(
+        "127.0.0.1".to_string(),
+        0,
+        config(BridgeBackend::Mock),
+        None,
+        true,
+    )
+    .await
+    .unwrap();
+    assert_eq!(server.bridge_factory.backend(), BridgeBackend::Mock);
+}
+
+#[tokio::test]
+async fn test_default_server_uses_real_backend() {
+    let server = WalletServer::new("127.0.0.1".to_string(), 0, config(BridgeBackend::Real), None)
+        .await
+        .unwrap();
+    assert_eq!(server.bridge_factory.backend(), BridgeBackend::Real);
+}
+
+#[tokio::test]
+async fn test_test_server_uses_mock_backend_and_reports_it() {
+    // 空 networks 时 handler 回退到 eth/polygon 支持列表
+    let mut cfg = config(BridgeBackend::Real);
+    cfg.blockchain.networks.clear();
+    let server = WalletServer::new_for_test("127.0.0.1".to_string(), 0, cfg, api_key(), None)
+    .await
+    .unwrap();
+    assert_eq!(server.bridge_factory.backend(), BridgeBackend::Mock);
+    server.wallet_manager.create_wallet("bridge_src", "bridge-test-password", false).await.unwrap();
+
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let health: Value = app.get("/api/health").await.json();
+    assert_eq!(health["bridge_backend"], "mock");
+
+    let res = app
+        .post("/api/bridge")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({
+            "from_wallet": "bridge_src",
+            "from_chain": "eth",
+            "to_chain": "polygon",
+            "token": "USDC",
+            "amount": "1.0"
+        }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert!(body["bridge_tx_id"].as_str().unwrap().starts_with("0x_simulated"));
+}
+
+#[tokio::test]
+async fn test_unknown_route_returns_unsupported_bridge_route() {
+    let mut cfg = config(BridgeBackend::Mock);
+    cfg.blockchain.networks.insert(
+        "bsc".to_string(),
+        defi_hot_wallet::core::config::NetworkConfig {
+            name: "bsc".to_string(),
+            rpc_url: "http://127.0.0.1:1".to_string(),
+            chain_id: 56,
+            explorer_url_template: None,
+            rate_limit: None,
+            fallback_endpoints: Vec::new(),
+            pending_expiry_seconds: None,
+        },
+    );
+    cfg.blockchain.networks.insert(
+        "polygon".to_string(),
+        defi_hot_wallet::core::config::NetworkConfig {
+            name: "polygon".to_string(),
+            rpc_url: "http://127.0.0.1:1".to_string(),
+            chain_id: 137,
+            explorer_url_template: None,
+            rate_limit: None,
+            fallback_endpoints: Vec::new(),
+            pending_expiry_seconds: None,
+        },
+    );
+    let server = WalletServer::new_for_test("127.0.0.1".to_string(), 0, cfg, api_key(), None)
+        .await
+        .unwrap();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let res = app
+        .post("/api/bridge")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({
+            "from_wallet": "any",
+            "from_chain": "polygon",
+            "to_chain": "bsc",
+            "token": "USDC",
+            "amount": "1.0"
+        }))
+        .await;
+    res.assert_status_bad_request();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "UNSUPPORTED_BRIDGE_ROUTE");
+    assert!(body["error"]["message"].as_str().unwrap().contains("eth->polygon"));
+}
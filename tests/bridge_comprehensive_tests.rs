@@ -53,6 +53,10 @@ fn test_bridge_transaction_creation() {
         updated_at: now,
         fee_amount: Some("100".to_string()),
         estimated_completion_time: None,
+        amount_minimal_source: None,
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
     };
     
     assert_eq!(tx.id, "test_tx_001");
@@ -80,6 +84,10 @@ fn test_bridge_transaction_fields() {
         updated_at: now,
         fee_amount: Some("500".to_string()),
         estimated_completion_time: Some(now),
+        amount_minimal_source: None,
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
     };
     
     // 验证字段
@@ -111,6 +119,10 @@ async fn test_bridge_relay_transfer_insufficient_balance() {
         updated_at: now,
         fee_amount: None,
         estimated_completion_time: None,
+        amount_minimal_source: None,
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
     };
     
     // 验证金额过大会被拒绝
@@ -236,6 +248,10 @@ async fn test_bridge_concurrent_transfers() {
                 updated_at: now,
                 fee_amount: None,
                 estimated_completion_time: None,
+                amount_minimal_source: None,
+                amount_minimal_dest: None,
+                token_address_source: None,
+                token_address_dest: None,
             };
             
             // 模拟处理
@@ -278,6 +294,10 @@ async fn test_bridge_status_transitions() {
         updated_at: now,
         fee_amount: None,
         estimated_completion_time: None,
+        amount_minimal_source: None,
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
     };
     
     // Initiated → InTransit
@@ -340,6 +360,10 @@ mod proptest_bridge {
                 updated_at: now,
                 fee_amount: None,
                 estimated_completion_time: None,
+                amount_minimal_source: None,
+                amount_minimal_dest: None,
+                token_address_source: None,
+                token_address_dest: None,
             };
             
             prop_assert_eq!(tx.amount, amount_str);
@@ -362,6 +386,10 @@ mod proptest_bridge {
                 updated_at: now,
                 fee_amount: None,
                 estimated_completion_time: None,
+                amount_minimal_source: None,
+                amount_minimal_dest: None,
+                token_address_source: None,
+                token_address_dest: None,
             };
             
             prop_assert_eq!(tx.from_chain, name);
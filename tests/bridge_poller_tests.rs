@@ -0,0 +1,105 @@
+//! 跨链桥轮询：后台任务把 Initiated / InTransit 的跨链transaction推进到终态，
+//! 查询失败的transaction单独退避，不挡住其他transaction
+
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+use defi_hot_wallet::blockchain::bridge::{BridgeFactory, BridgeTransaction, BridgeTransactionStatus};
+use defi_hot_wallet::core::config::{BridgeBackend, BridgePollerConfig};
+use defi_hot_wallet::ops::bridge_poller::{BridgePollReport, BridgePoller};
+use defi_hot_wallet::ops::jobs::Job;
+use defi_hot_wallet::storage::WalletStorage;
+
+fn transfer(id: &str, to_chain: &str, minutes_ago: i64) -> BridgeTransaction {
+    let at = Utc::now() - Duration::minutes(minutes_ago);
+    BridgeTransaction {
+        id: id.to_string(),
+        from_wallet: "treasury".to_string(),
+        from_chain: "eth".to_string(),
+        to_chain: to_chain.to_string(),
+        token: "USDC".to_string(),
+        amount: "25".to_string(),
+        status: BridgeTransactionStatus::Initiated,
+        source_tx_hash: None,
+        destination_tx_hash: None,
+        created_at: at,
+        updated_at: at,
+        fee_amount: None,
+        estimated_completion_time: None,
+        amount_minimal_source: None,
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
+    }
+}
+
+async fn setup(backend: BridgeBackend, config: BridgePollerConfig) -> (BridgePoller, Arc<WalletStorage>) {
+    let storage = Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap());
+    // mock 后端的 eth->bsc 由 EthereumToBSCBridge 处理
+    let bridges = Arc::new(BridgeFactory::new(backend));
+    (BridgePoller::new(config, storage.clone(), bridges), storage)
+}
+
+#[tokio::test]
+async fn test_initiated_transfer_is_driven_to_completed() {
+    let (poller, storage) = setup(BridgeBackend::Mock, BridgePollerConfig::default()).await;
+    let id = format!("0x_simulated_tx_{}", uuid::Uuid::new_v4());
+    storage.store_bridge_transaction(&transfer(&id, "bsc", 5)).await.unwrap();
+    storage.store_bridge_transaction(&transfer("0xbridge_marked_failed", "bsc", 4)).await.unwrap();
+    assert_eq!(storage.list_pending_bridge_transactions(10).await.unwrap().len(), 2);
+
+    let report = poller.run_once(&CancellationToken::new()).await;
+    assert_eq!(report, BridgePollReport { checked: 2, completed: 1, failed: 1, ..Default::default() });
+    assert_eq!(storage.get_bridge_transaction(&id).await.unwrap().status, BridgeTransactionStatus::Completed);
+    assert!(matches!(
+        storage.get_bridge_transaction("0xbridge_marked_failed").await.unwrap().status,
+        BridgeTransactionStatus::Failed(_)
+    ));
+
+    // 终态不再轮询
+    assert!(storage.list_pending_bridge_transactions(10).await.unwrap().is_empty());
+    assert_eq!(poller.run_once(&CancellationToken::new()).await, BridgePollReport::default());
+}
+
+#[tokio::test]
+async fn test_failing_transfer_backs_off_without_starving_others() {
+    let config = BridgePollerConfig { batch_size: 1, ..Default::default() };
+    let (poller, storage) = setup(BridgeBackend::Mock, config).await;
+    // 不支持的路由：每次查询都失败
+    storage.store_bridge_transaction(&transfer("0x_simulated_tx_stuck", "solana", 10)).await.unwrap();
+    let id = format!("0x_simulated_tx_{}", uuid::Uuid::new_v4());
+    storage.store_bridge_transaction(&transfer(&id, "bsc", 5)).await.unwrap();
+
+    let report = poller.run_once(&CancellationToken::new()).await;
+    assert_eq!(report, BridgePollReport { checked: 1, errors: 1, ..Default::default() });
+
+    // 退避中的transaction不占批次
+    let report = poller.run_once(&CancellationToken::new()).await;
+    assert_eq!(report, BridgePollReport { checked: 1, completed: 1, deferred: 1, ..Default::default() });
+    assert!(poller.run(CancellationToken::new()).await.is_ok());
+    assert_eq!(storage.get_bridge_transaction(&id).await.unwrap().status, BridgeTransactionStatus::Completed);
+    assert_eq!(
+        storage.get_bridge_transaction("0x_simulated_tx_stuck").await.unwrap().status,
+        BridgeTransactionStatus::Initiated
+    );
+}
+
+#[tokio::test]
+async fn test_cycle_fails_when_every_check_fails() {
+    // real 后端尚无可用的桥：查询全部失败
+    let (poller, storage) = setup(BridgeBackend::Real, BridgePollerConfig::default()).await;
+    storage.store_bridge_transaction(&transfer("0x_simulated_tx_real", "bsc", 1)).await.unwrap();
+    assert!(poller.run(CancellationToken::new()).await.is_err());
+
+    // 已取消时不再发起查询
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let (poller, storage) = setup(BridgeBackend::Mock, BridgePollerConfig::default()).await;
+    storage.store_bridge_transaction(&transfer("0x_simulated_tx_cancelled", "bsc", 1)).await.unwrap();
+    poller.run_once(&cancel).await;
+    assert_eq!(
+        storage.get_bridge_transaction("0x_simulated_tx_cancelled").await.unwrap().status,
+        BridgeTransactionStatus::Initiated
+    );
+}
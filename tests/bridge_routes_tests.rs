@@ -0,0 +1,169 @@
+//! 跨链路由发现：LI.FI 发现缓存、/api/bridge/routes 与转账前的路由校验
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use httpmock::{Method, MockServer};
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::bridge::{Bridge, LiFiBridge};
+use defi_hot_wallet::core::config::{BlockchainConfig, SecurityConfig, StorageConfig, WalletConfig};
+
+async fn mock_lifi(server: &MockServer) -> [httpmock::Mock<'_>; 3] {
+    let chains = server
+        .mock_async(|when, then| {
+            when.method(Method::GET).path("/chains");
+            then.status(200).json_body(json!({
+                "chains": [{ "id": 1, "key": "eth" }, { "id": 137, "key": "pol" }, { "id": 324, "key": "era" }]
+            }));
+        })
+        .await;
+    let tools = server
+        .mock_async(|when, then| {
+            when.method(Method::GET).path("/tools");
+            then.status(200).json_body(json!({
+                "bridges": [
+                    { "key": "stargate", "supportedChains": [
+                        { "fromChainId": 1, "toChainId": 137 },
+                        { "fromChainId": 137, "toChainId": 1 }
+                    ]},
+                    // 324 不在wallet支持的network里，应被忽略
+                    { "key": "across", "supportedChains": [
+                        { "fromChainId": 1, "toChainId": 137 },
+                        { "fromChainId": 1, "toChainId": 324 }
+                    ]}
+                ],
+                "exchanges": []
+            }));
+        })
+        .await;
+    let tokens = server
+        .mock_async(|when, then| {
+            when.method(Method::GET).path("/tokens").query_param("chains", "1,137");
+            then.status(200).json_body(json!({
+                "tokens": {
+                    "1": [{ "symbol": "USDC" }, { "symbol": "ETH" }, { "symbol": "WBTC" }],
+                    "137": [{ "symbol": "USDC" }, { "symbol": "WBTC" }, { "symbol": "POL" }]
+                }
+            }));
+        })
+        .await;
+    [chains, tools, tokens]
+}
+
+#[tokio::test]
+async fn test_lifi_discovery_is_cached_until_ttl() {
+    let server = MockServer::start_async().await;
+    let mocks = mock_lifi(&server).await;
+    let bridge = LiFiBridge::with_base_url(&server.base_url()).with_cache_ttl(Duration::from_millis(300));
+
+    let description = bridge.describe().await.unwrap();
+    assert_eq!(description.backend, "lifi");
+    let routes: Vec<(&str, &str, Vec<String>)> = description
+        .routes
+        .iter()
+        .map(|r| (r.from_chain.as_str(), r.to_chain.as_str(), r.tokens.clone()))
+        .collect();
+    let common = vec!["USDC".to_string(), "WBTC".to_string()];
+    assert_eq!(routes, vec![("eth", "polygon", common.clone()), ("polygon", "eth", common)]);
+
+    // TTL 内直接用缓存
+    assert_eq!(bridge.describe().await.unwrap(), description);
+    for mock in &mocks {
+        mock.assert_hits_async(1).await;
+    }
+
+    tokio::time::sleep(Duration::from_millis(350)).await;
+    bridge.describe().await.unwrap();
+    for mock in &mocks {
+        mock.assert_hits_async(2).await;
+    }
+}
+
+fn create_test_config() -> WalletConfig {
+    WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(1),
+            connection_timeout_seconds: Some(5),
+            read_database_url: None,
+        },
+        blockchain: BlockchainConfig { networks: HashMap::new() },
+        quantum_safe: false,
+        multi_sig_threshold: 2,
+        derivation: Default::default(),
+        security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
+    }
+}
+
+async fn test_server() -> TestServer {
+    let server = WalletServer::new_for_test("127.0.0.1".to_string(), 0, create_test_config(), None, None)
+        .await
+        .unwrap();
+    TestServer::new(server.create_router().await).unwrap()
+}
+
+#[tokio::test]
+async fn test_routes_endpoint_lists_mock_matrix() {
+    let server = test_server().await;
+
+    let response = server.get("/api/bridge/routes").add_query_param("from", "eth").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Value = response.json();
+    let routes = body["routes"].as_array().unwrap();
+    assert!(routes.iter().all(|r| r["from_chain"] == "eth"));
+
+    let usdc_to_bsc = routes.iter().find(|r| r["to_chain"] == "bsc" && r["token"] == "USDC").unwrap();
+    assert_eq!(usdc_to_bsc["backends"][0]["backend"], "mock-eth-bsc");
+    assert_eq!(usdc_to_bsc["backends"][0]["max_amount"], "1000000");
+    assert_eq!(usdc_to_bsc["health"]["recent_transfers"], 0);
+    assert!(usdc_to_bsc["health"]["failure_rate"].is_null());
+    assert!(routes.iter().any(|r| r["to_chain"] == "polygon" && r["token"] == "MATIC"));
+}
+
+#[tokio::test]
+async fn test_bridge_rejects_unsupported_token_before_backend() {
+    let server = test_server().await;
+
+    let response = server
+        .post("/api/bridge")
+        .json(&json!({
+            "from_wallet": "missing_wallet",
+            "from_chain": "eth",
+            "to_chain": "polygon",
+            "token": "DOGE",
+            "amount": "1.0"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["error"]["code"], "UNSUPPORTED_BRIDGE_TOKEN");
+    assert_eq!(
+        body["error"]["message"],
+        "Token DOGE is not bridgeable on eth->polygon. Supported tokens: DAI, ETH, MATIC, USDC, USDT"
+    );
+}
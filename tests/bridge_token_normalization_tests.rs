@@ -0,0 +1,288 @@
+//! 桥接代币归一化：两端按符号 / address解析、6 与 18 位小数之间的换算、
+//! 未配置代币时指明缺失的一端、限额按最小单位比较，以及旧记录仍可读取
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::bridge::normalize::normalize;
+use defi_hot_wallet::blockchain::bridge::{BridgeFactory, BridgeSide, RouteRejection};
+use defi_hot_wallet::core::config::{BridgeBackend, NetworkConfig, StorageConfig, TokenAssetConfig, WalletConfig};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::WalletStorage;
+use defi_hot_wallet::token_registry::TokenRegistry;
+
+const ETH_USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+const BSC_USDC: &str = "0x8ac76a51cc950d9822d68b83fe1ad97b32cd580d";
+const POLYGON_USDC: &str = "0x3c499c542cef5e3811e1192ce70d8cc03d5c3359";
+const API_KEY: &str = "bridge-normalization-admin-key";
+const PASSWORD: &str = "Br1dge!Normal#2024";
+
+async fn memory_storage() -> Arc<WalletStorage> {
+    Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap())
+}
+
+/// 内置的主网资产列表
+async fn default_tokens() -> TokenRegistry {
+    TokenRegistry::from_config(&WalletConfig::default(), memory_storage().await).unwrap()
+}
+
+fn asset(address: Option<&str>, decimals: u32, asset: Option<&str>) -> TokenAssetConfig {
+    TokenAssetConfig { address: address.map(str::to_string), decimals, asset: asset.map(str::to_string) }
+}
+
+#[tokio::test]
+async fn test_symbol_and_address_resolve_on_both_legs() {
+    let tokens = default_tokens().await;
+
+    // 符号不区分大小写
+    let by_symbol = normalize(&tokens, "eth", "polygon", "usdc", "2.5").unwrap();
+    assert_eq!(by_symbol.asset(), "USDC");
+    assert_eq!(by_symbol.source.address.as_deref(), Some(ETH_USDC));
+    assert_eq!(by_symbol.destination.address.as_deref(), Some(POLYGON_USDC));
+    assert_eq!(by_symbol.amount_source.minimal(), 2_500_000);
+    assert_eq!(by_symbol.amount_destination.minimal(), 2_500_000);
+
+    // 源链合约address，目标链按资产找到自己的合约
+    let by_address = normalize(&tokens, "eth", "polygon", "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "2.5").unwrap();
+    assert_eq!(by_address, by_symbol);
+
+    // polygon 上的 ETH 是 WETH，到 eth 上是原生币
+    let weth = normalize(&tokens, "polygon", "eth", "ETH", "0.25").unwrap();
+    assert_eq!(weth.source.symbol, "WETH");
+    assert_eq!(weth.destination.symbol, "ETH");
+    assert_eq!(weth.destination.address, None);
+    assert_eq!(weth.amount_destination.minimal(), 250_000_000_000_000_000);
+}
+
+#[tokio::test]
+async fn test_six_and_eighteen_decimals_convert_exactly() {
+    let tokens = default_tokens().await;
+
+    let to_bsc = normalize(&tokens, "eth", "bsc", "USDC", "1.5").unwrap();
+    assert_eq!((to_bsc.source.decimals, to_bsc.destination.decimals), (6, 18));
+    assert_eq!(to_bsc.amount_source.minimal(), 1_500_000);
+    assert_eq!(to_bsc.amount_destination.minimal(), 1_500_000_000_000_000_000);
+    assert_eq!(to_bsc.destination.address.as_deref(), Some(BSC_USDC));
+    assert_eq!(to_bsc.amount(), "1.5");
+
+    let to_eth = normalize(&tokens, "bsc", "eth", "USDC", "1.000001").unwrap();
+    assert_eq!(to_eth.amount_destination.minimal(), 1_000_001);
+
+    // bsc 能表示 0.0000001，eth 上的 6 位小数不能：拒绝而不是截断
+    let err = normalize(&tokens, "bsc", "eth", "USDC", "0.0000001").unwrap_err();
+    assert_eq!(
+        err,
+        RouteRejection::AmountPrecision {
+            token: "USDC".to_string(),
+            amount: "0.0000001".to_string(),
+            chain: "eth".to_string(),
+            decimals: 6,
+        }
+    );
+    assert_eq!(err.code(), "BRIDGE_AMOUNT_PRECISION");
+
+    // 源链本身的精度不够同样报源链
+    let err = normalize(&tokens, "eth", "bsc", "USDC", "0.0000001").unwrap_err();
+    assert!(matches!(err, RouteRejection::AmountPrecision { ref chain, .. } if chain == "eth"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_unconfigured_token_names_the_missing_side() {
+    let tokens = TokenRegistry::new(memory_storage().await)
+        .with_asset("eth", "FOO", &asset(Some("0x1111111111111111111111111111111111111111"), 18, None))
+        .unwrap()
+        .with_asset("eth", "USDX", &asset(Some("0x2222222222222222222222222222222222222222"), 6, None))
+        .unwrap()
+        .with_asset("bsc", "USDX", &asset(Some("0x3333333333333333333333333333333333333333"), 18, Some("USDC")))
+        .unwrap();
+
+    let err = normalize(&tokens, "eth", "bsc", "foo", "1").unwrap_err();
+    assert_eq!(
+        err,
+        RouteRejection::UnconfiguredToken {
+            token: "foo".to_string(),
+            chain: "bsc".to_string(),
+            side: BridgeSide::Destination
+        }
+    );
+    assert_eq!(err.to_string(), "Token foo is not configured on the destination chain bsc");
+    assert_eq!(err.code(), "UNCONFIGURED_BRIDGE_TOKEN");
+
+    let unknown = "0x4444444444444444444444444444444444444444";
+    let err = normalize(&tokens, "eth", "bsc", unknown, "1").unwrap_err();
+    assert!(
+        matches!(err, RouteRejection::UnconfiguredToken { side: BridgeSide::Source, ref chain, .. } if chain == "eth")
+    );
+
+    // 同名符号在两条链上是不同的资产
+    let err = normalize(&tokens, "eth", "bsc", "USDX", "1").unwrap_err();
+    assert_eq!(err.to_string(), "Token USDX is USDX on eth but USDC on bsc");
+    assert_eq!(err.code(), "BRIDGE_ASSET_MISMATCH");
+}
+
+#[tokio::test]
+async fn test_limits_compare_minimal_units() {
+    let tokens = default_tokens().await;
+    let factory = BridgeFactory::new(BridgeBackend::Mock);
+
+    // mock 限额 0.000001..1000000：6 位小数的下限正好是 1 个最小单位
+    let at_min = factory.check_transfer(&tokens, "eth", "bsc", "USDC", "0.000001").await.unwrap();
+    assert_eq!(at_min.amount_source.minimal(), 1);
+    assert!(factory.check_transfer(&tokens, "eth", "bsc", "USDC", "1000000").await.is_ok());
+
+    let err = factory.check_transfer(&tokens, "bsc", "eth", "USDC", "1000000.000001").await.unwrap_err();
+    assert_eq!(err.code(), "BRIDGE_AMOUNT_OUT_OF_RANGE");
+    assert_eq!(
+        err.to_string(),
+        "Amount 1000000.000001 USDC is outside the bridge limits: mock-eth-bsc 0.000001..1000000"
+    );
+
+    // address同样要先经过路由表
+    let transfer = factory.check_transfer(&tokens, "eth", "bsc", ETH_USDC, "3").await.unwrap();
+    assert_eq!(transfer.amount_destination.minimal(), 3_000_000_000_000_000_000);
+    let err = factory.check_transfer(&tokens, "eth", "bsc", "DOGE", "1").await.unwrap_err();
+    assert_eq!(err.code(), "UNSUPPORTED_BRIDGE_TOKEN");
+}
+
+#[tokio::test]
+async fn test_legacy_rows_without_normalized_columns_stay_readable() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("legacy.db").display());
+
+    // 归一化之前的表结构与记录
+    let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+    sqlx::query(
+        "CREATE TABLE bridge_transactions (id TEXT PRIMARY KEY, from_wallet TEXT NOT NULL, from_chain TEXT NOT NULL, \
+         to_chain TEXT NOT NULL, token TEXT NOT NULL, amount TEXT NOT NULL, status TEXT NOT NULL, source_tx_hash TEXT, \
+         destination_tx_hash TEXT, created_at DATETIME NOT NULL, updated_at DATETIME NOT NULL, fee_amount TEXT, \
+         estimated_completion_time DATETIME)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO bridge_transactions (id, from_wallet, from_chain, to_chain, token, amount, status, created_at, \
+         updated_at) VALUES ('legacy-1', 'old_wallet', 'eth', 'polygon', 'USDC', '12.5', '\"Completed\"', \
+         '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool.close().await;
+
+    let storage = WalletStorage::new_with_url(&url).await.unwrap();
+    let legacy = storage.get_bridge_transaction("legacy-1").await.unwrap();
+    assert_eq!((legacy.token.as_str(), legacy.amount.as_str()), ("USDC", "12.5"));
+    assert_eq!(legacy.amount_minimal_source, None);
+    assert_eq!(legacy.amount_minimal_dest, None);
+    assert_eq!(legacy.token_address_source, None);
+    assert_eq!(legacy.token_address_dest, None);
+
+    let (rows, total) = storage.list_bridge_transactions(Some("old_wallet"), None, None, 0, 10).await.unwrap();
+    assert_eq!((rows.len(), total), (1, 1));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_status_and_history_show_each_chain_in_its_own_decimals() {
+    let mut config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    config.blockchain.networks.insert(
+        "bsc".to_string(),
+        NetworkConfig {
+            name: "bsc".to_string(),
+            rpc_url: "http://127.0.0.1:1".to_string(),
+            chain_id: 56,
+            explorer_url_template: None,
+            rate_limit: None,
+            fallback_endpoints: Vec::new(),
+            pending_expiry_seconds: None,
+        },
+    );
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    server.wallet_manager.create_wallet("bridge_decimals", PASSWORD, false).await.unwrap();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let res = app
+        .post("/api/bridge")
+        .add_header("Authorization", API_KEY)
+        .json(&json!({
+            "from_wallet": "bridge_decimals",
+            "from_chain": "eth",
+            "to_chain": "bsc",
+            "token": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            "amount": "1.50"
+        }))
+        .await;
+    res.assert_status_ok();
+    let bridge_id = res.json::<Value>()["bridge_id"].as_str().unwrap().to_string();
+
+    let res = app.get(&format!("/api/bridge/{}/status", bridge_id)).add_header("Authorization", API_KEY).await;
+    res.assert_status_ok();
+    let status: Value = res.json();
+    assert_eq!(status["token"], "USDC");
+    assert_eq!(status["amount"], "1.5");
+    assert_eq!(
+        status["source"],
+        json!({
+            "chain": "eth",
+            "token_address": ETH_USDC,
+            "amount_minimal": "1500000",
+            "amount": "1.5",
+            "decimals": 6
+        })
+    );
+    assert_eq!(
+        status["destination"],
+        json!({
+            "chain": "bsc",
+            "token_address": BSC_USDC,
+            "amount_minimal": "1500000000000000000",
+            "amount": "1.5",
+            "decimals": 18
+        })
+    );
+
+    let res = app.get("/api/bridge/history").add_header("Authorization", API_KEY).await;
+    res.assert_status_ok();
+    let history: Value = res.json();
+    let item = history["items"].as_array().unwrap().iter().find(|i| i["id"] == bridge_id.as_str()).unwrap();
+    assert_eq!(item["destination"]["amount_minimal"], "1500000000000000000");
+
+    // 未配置在源链上的合约address
+    let res = app
+        .post("/api/bridge")
+        .add_header("Authorization", API_KEY)
+        .json(&json!({
+            "from_wallet": "bridge_decimals",
+            "from_chain": "eth",
+            "to_chain": "bsc",
+            "token": "0x4444444444444444444444444444444444444444",
+            "amount": "1"
+        }))
+        .await;
+    assert_eq!(res.status_code(), axum::http::StatusCode::BAD_REQUEST);
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "UNCONFIGURED_BRIDGE_TOKEN");
+    assert_eq!(
+        body["error"]["message"],
+        "Token 0x4444444444444444444444444444444444444444 is not configured on the source chain eth"
+    );
+}
@@ -0,0 +1,148 @@
+//! Bitcoin xpub / descriptor 导出（`/api/wallets/:name/btc/descriptor`）集成测试
+#![cfg(feature = "bitcoin")]
+
+mod util;
+
+use std::str::FromStr;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use bitcoin::bip32::Xpub;
+use serde_json::Value;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::wallet_manager::btc_descriptor::descriptor_checksum;
+
+const API_KEY: &str = "btc-descriptor-admin-key";
+const SESSION: &str = "btc-descriptor-session";
+const ADDRESS: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+const WALLET_PASSWORD: &str = "Watch0nlyExport";
+const PBKDF2_FIXTURE: &str = include_str!("fixtures/keystore/pbkdf2_testvector.json");
+
+struct Harness {
+    server: WalletServer,
+    app: TestServer,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = util::memory_config();
+    config.security.pbkdf2_iterations = 1_000;
+    let server = util::test_server(dir.path(), config, Some(API_KEY)).await;
+
+    let user_id = util::sign_in(&server, "watchers@example.com", SESSION).await;
+    for name in ["cold_hd", "imported"] {
+        server.user_db.link_wallet(&user_id, name, ADDRESS, None).await.unwrap();
+    }
+    server.wallet_manager.create_wallet("cold_hd", WALLET_PASSWORD, false).await.unwrap();
+    server
+        .wallet_manager
+        .import_keystore_v3("imported", PBKDF2_FIXTURE, "testpassword", WALLET_PASSWORD)
+        .await
+        .unwrap();
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { server, app, _dir: dir }
+}
+
+async fn export(h: &Harness, wallet: &str, query: &str, password: &str) -> axum_test::TestResponse {
+    h.app
+        .get(&format!("/api/wallets/{}/btc/descriptor{}", wallet, query))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .add_header("X-Wallet-Password", password.to_string())
+        .await
+}
+
+fn assert_checksummed(descriptor: &str) {
+    let (body, checksum) = descriptor.split_once('#').unwrap();
+    assert_eq!(descriptor_checksum(body).unwrap(), checksum);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_export_segwit_descriptor_is_audited() {
+    let h = build().await;
+
+    let res = export(&h, "cold_hd", "", WALLET_PASSWORD).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["address_type"], "segwit");
+    assert_eq!(body["derivation_path"], "m/84'/0'/0'");
+
+    let fingerprint = body["fingerprint"].as_str().unwrap();
+    let xpub = body["xpub"].as_str().unwrap();
+    assert_eq!(fingerprint.len(), 8);
+    let parsed = Xpub::from_str(xpub).unwrap();
+    assert_eq!(parsed.depth, 3);
+    assert!(body["zpub"].as_str().unwrap().starts_with("zpub"));
+
+    let descriptor = body["descriptor"].as_str().unwrap();
+    assert!(descriptor.starts_with(&format!("wpkh([{}/84h/0h/0h]{}/0/*)#", fingerprint, xpub)));
+    assert_checksummed(descriptor);
+    assert_checksummed(body["change_descriptor"].as_str().unwrap());
+    assert!(!res.text().contains("prv"));
+
+    let logs = h.server.storage.get_audit_logs(Some("cold_hd")).await.unwrap();
+    let entry = logs.iter().find(|l| l.action == "btc_descriptor.export").expect("export audited");
+    assert!(entry.details.as_deref().unwrap().contains(fingerprint));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_export_taproot_account() {
+    let h = build().await;
+
+    let res = export(&h, "cold_hd", "?type=taproot&account=2", WALLET_PASSWORD).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["derivation_path"], "m/86'/0'/2'");
+    assert!(body.get("zpub").is_none());
+    let descriptor = body["descriptor"].as_str().unwrap();
+    assert!(descriptor.starts_with("tr(["));
+    assert!(descriptor.contains("/86h/0h/2h]xpub"));
+    assert_checksummed(descriptor);
+
+    // 同一主密钥，不同账户得到不同 xpub，但指纹相同
+    let segwit: Value = export(&h, "cold_hd", "?type=segwit", WALLET_PASSWORD).await.json();
+    assert_ne!(segwit["xpub"], body["xpub"]);
+    assert_eq!(segwit["fingerprint"], body["fingerprint"]);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_export_rejections() {
+    let h = build().await;
+
+    // 导入的单私钥wallet没有 BIP32 树
+    let res = export(&h, "imported", "", WALLET_PASSWORD).await;
+    res.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(res.json::<Value>()["error"]["code"], "UNSUPPORTED_WALLET_KIND");
+
+    let res = export(&h, "cold_hd", "", "Wr0ngPassword!").await;
+    res.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_PASSWORD");
+
+    let res = export(&h, "cold_hd", "?type=p2sh", WALLET_PASSWORD).await;
+    res.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_ADDRESS_TYPE");
+
+    let res = h
+        .app
+        .get("/api/wallets/cold_hd/btc/descriptor")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await;
+    res.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(res.json::<Value>()["error"]["code"], "MISSING_PARAMETER");
+
+    let res = h
+        .app
+        .get("/api/wallets/cold_hd/btc/descriptor")
+        .add_header("X-Wallet-Password", WALLET_PASSWORD)
+        .await;
+    res.assert_status(StatusCode::UNAUTHORIZED);
+
+    // 失败的导出不记审计
+    let logs = h.server.storage.get_audit_logs(Some("cold_hd")).await.unwrap();
+    assert!(logs.iter().all(|l| l.action != "btc_descriptor.export"));
+}
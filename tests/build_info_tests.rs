@@ -0,0 +1,153 @@
+//! 构建与运行时信息：build script 输出的 feature 列表与本次测试构建一致、
+//! `GET /api/version` 的结构与认证、配置校验和对空白/键顺序不敏感而对取值敏感、
+//! uptime 单调递增
+
+use axum_test::TestServer;
+use serde_json::Value;
+use std::time::Duration;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::build_info::{self, BuildInfo, RuntimeInfo};
+use defi_hot_wallet::core::config::{AdmissionConfig, BridgeBackend, StorageConfig, WalletConfig};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::SCHEMA_VERSION;
+
+const API_KEY: &str = "build-info-admin-key-0123456789ab";
+const SESSION: &str = "build-info-session-token";
+
+fn config() -> WalletConfig {
+    WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    }
+}
+
+async fn build() -> (TestServer, tempfile::TempDir) {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    std::env::set_var("TEST_SKIP_DECRYPT", "1");
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config(),
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    server.session_store.register_token(SESSION, "build-info-user", 3600).await;
+    (TestServer::new(server.create_router().await).unwrap(), dir)
+}
+
+#[test]
+fn test_feature_list_matches_the_test_build() {
+    // Cargo.toml [features] 中声明的全部 feature
+    let declared = [
+        ("ai-anomaly-detection", cfg!(feature = "ai-anomaly-detection")),
+        ("bitcoin", cfg!(feature = "bitcoin")),
+        ("bls-tests", cfg!(feature = "bls-tests")),
+        ("bundled-resources", cfg!(feature = "bundled-resources")),
+        ("database", cfg!(feature = "database")),
+        ("default", cfg!(feature = "default")),
+        ("ethereum", cfg!(feature = "ethereum")),
+        ("ledger", cfg!(feature = "ledger")),
+        ("memlock", cfg!(feature = "memlock")),
+        ("msgpack", cfg!(feature = "msgpack")),
+        ("shamir-impl", cfg!(feature = "shamir-impl")),
+        ("sop_patch_tests", cfg!(feature = "sop_patch_tests")),
+        ("std", cfg!(feature = "std")),
+        ("strict_security", cfg!(feature = "strict_security")),
+        ("test-env", cfg!(feature = "test-env")),
+        ("trezor", cfg!(feature = "trezor")),
+        ("yaml", cfg!(feature = "yaml")),
+    ];
+    let expected: Vec<&str> = declared.iter().filter(|(_, on)| *on).map(|(name, _)| *name).collect();
+    assert_eq!(build_info::enabled_features(), expected);
+
+    let build = BuildInfo::current();
+    assert_eq!(build.features, expected);
+    assert_eq!(build.version, env!("CARGO_PKG_VERSION"));
+    assert!(build.rustc_version.starts_with("rustc "), "{}", build.rustc_version);
+    assert!(chrono::DateTime::parse_from_rfc3339(&build.build_timestamp).is_ok());
+    assert!(build.git_commit == "unknown" || build.git_commit.chars().all(|c| c.is_ascii_hexdigit()));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_version_endpoint_shape() {
+    let (app, _dir) = build().await;
+    app.get("/api/version").await.assert_status_unauthorized();
+
+    let res = app.get("/api/version").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["build"]["version"], env!("CARGO_PKG_VERSION"));
+    for field in ["git_commit", "git_dirty", "rustc_version", "build_timestamp", "profile", "features"] {
+        assert!(!body["build"][field].is_null(), "build.{}", field);
+    }
+    let runtime = &body["runtime"];
+    assert_eq!(runtime["schema_version"], SCHEMA_VERSION);
+    // the checksum covers the running config; new_for_test switches to mock bridges
+    let running = WalletConfig { bridge_backend: BridgeBackend::Mock, ..config() };
+    assert_eq!(runtime["config_checksum"], build_info::config_checksum(&running));
+    assert_eq!(runtime["subsystems"]["telemetry"], true);
+    assert_eq!(runtime["subsystems"]["quantum_safe"], false);
+    assert_eq!(runtime["subsystems"]["hardware_trezor"], cfg!(feature = "trezor"));
+    assert!(runtime["started_at"].is_string() && runtime["uptime_secs"].is_u64());
+
+    // 会话 token 同样可读
+    let res = app.get("/api/version").add_header("Authorization", format!("Bearer {}", SESSION)).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["build"], body["build"]);
+
+    // health 与 admin summary 带上同样的信息
+    let health: Value = app.get("/api/health").await.json();
+    assert_eq!(health["schema_version"], SCHEMA_VERSION);
+    let summary: Value = app.get("/api/admin/summary").add_header("X-API-KEY", API_KEY).await.json();
+    assert_eq!(summary["build"], body["build"]);
+    assert_eq!(summary["runtime"]["config_checksum"], runtime["config_checksum"]);
+}
+
+#[test]
+fn test_config_checksum_ignores_formatting_but_not_values() {
+    let with_admission = |toml_text: &str| WalletConfig {
+        admission: toml::from_str::<AdmissionConfig>(toml_text).unwrap(),
+        ..config()
+    };
+    let original = with_admission("max_in_flight = 8\nmax_queued = 32\n");
+    let reformatted = with_admission("\n\n   max_queued=32   # queue\n\tmax_in_flight    =    8\n\n");
+    let changed = with_admission("max_in_flight = 9\nmax_queued = 32\n");
+
+    let checksum = build_info::config_checksum(&original);
+    assert_eq!(checksum.len(), 64);
+    assert_eq!(build_info::config_checksum(&reformatted), checksum);
+    assert_ne!(build_info::config_checksum(&changed), checksum);
+    // 环境变量覆盖的值（如 DATABASE_URL）同样计入
+    let mut overridden = original.clone();
+    overridden.storage.database_url = "sqlite://./other.db".to_string();
+    assert_ne!(build_info::config_checksum(&overridden), checksum);
+    assert_eq!(RuntimeInfo::collect(&original, None).config_checksum, checksum);
+}
+
+#[test]
+fn test_uptime_is_monotonic() {
+    build_info::mark_process_start();
+    let started = build_info::started_at();
+    let mut last = build_info::uptime();
+    for _ in 0..5 {
+        std::thread::sleep(Duration::from_millis(5));
+        let now = build_info::uptime();
+        assert!(now > last);
+        last = now;
+    }
+    // 起始时间只在第一次确定
+    build_info::mark_process_start();
+    assert_eq!(build_info::started_at(), started);
+    assert!(RuntimeInfo::collect(&config(), Some(1)).uptime_secs <= build_info::uptime().as_secs());
+}
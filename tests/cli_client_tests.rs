@@ -0,0 +1,224 @@
+//! `hot_wallet client`：对运行在临时端口上的 WalletServer 执行 list / create / balance / send，
+//! 以及 HTTP 错误到退出码的映射
+//!
+//! 余额查询使用 MockProvider 支撑的 EthereumClient（响应按 LIFO 弹出）；广播经 MockChain 计数。
+
+use async_trait::async_trait;
+use ethers::providers::{MockProvider, Provider};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Bytes, TransactionReceipt, H256, U256};
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+use defi_hot_wallet::cli::amounts::NumberLocale;
+use defi_hot_wallet::cli::client::{
+    exit_code_for_status, ApiClient, ClientSendResult, EXIT_AUTH, EXIT_CONFLICT, EXIT_NOT_FOUND,
+};
+use defi_hot_wallet::cli::output::{Output, OutputFormat, Render, EXIT_FAILURE, EXIT_USAGE};
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "cli-client-admin-key-0123456789";
+const PASSWORD: &str = "Cl1ent!Vault#2024";
+const TO: &str = "0x5aeda56215b167893e80b4fe645ba6d5bab767de";
+
+/// 节点：只数广播了几笔
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    sent: Mutex<usize>,
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        *self.sent.lock().unwrap() += 1;
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        _tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        Ok(None)
+    }
+}
+
+struct Harness {
+    addr: SocketAddr,
+    mock: MockProvider,
+    chain: Arc<MockChain>,
+    _dir: tempfile::TempDir,
+}
+
+impl Harness {
+    fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    fn client(&self) -> ApiClient {
+        ApiClient::new(&self.url(), API_KEY).unwrap()
+    }
+
+    fn sent(&self) -> usize {
+        *self.chain.sent.lock().unwrap()
+    }
+}
+
+async fn start() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    let eth = EthereumClient::new_with_provider_and_chain(provider, "eth", 1);
+    let chain = Arc::new(MockChain::default());
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap()
+    .with_chain_clients(ClientRegistry::new().with_client("eth", Arc::new(eth)))
+    .with_broadcast_chain(chain.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = server.create_router().await;
+    tokio::spawn(async move { axum::serve(listener, router.into_make_service()).await.unwrap() });
+    Harness { addr, mock, chain, _dir: dir }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_wallet_lifecycle_through_client() {
+    let h = start().await;
+    let client = h.client();
+
+    assert!(client.list_wallets().await.unwrap().wallets.is_empty());
+
+    let created = client.create_wallet("treasury", PASSWORD, false).await.unwrap();
+    assert_eq!(created.name, "treasury");
+    assert!(created.address.starts_with("0x") && created.address.len() == 42, "{}", created.address);
+
+    let list = client.list_wallets().await.unwrap();
+    assert_eq!(list.wallets.len(), 1);
+    assert_eq!(list.wallets[0].name, "treasury");
+    let table = list.render_text(&NumberLocale::EN);
+    assert!(table.starts_with("NAME"), "{}", table);
+    assert!(table.lines().nth(1).unwrap().starts_with("treasury"), "{}", table);
+
+    h.mock.push(U256::from(1_500_000_000_000_000_000u64)).unwrap();
+    let balance = client.balance("treasury", "eth", PASSWORD).await.unwrap();
+    assert_eq!(balance.address, created.address);
+    assert!(balance.balance.starts_with("1.5"), "{}", balance.balance);
+
+    let sent = client.send("treasury", TO, "0.25", "eth", PASSWORD).await.unwrap();
+    let ClientSendResult::Sent { tx_hash, .. } = &sent else { panic!("expected a broadcast send, got {:?}", sent) };
+    assert!(tx_hash.starts_with("0x"));
+    assert_eq!(h.sent(), 1);
+
+    // --json：单行，可反序列化回结果
+    let json = Output { format: OutputFormat::Json, quiet: false, locale: NumberLocale::EN }.render(&sent);
+    assert!(!json.contains('\n'));
+    let value: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["status"], "sent");
+    assert_eq!(serde_json::from_value::<ClientSendResult>(value).unwrap(), sent);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_http_errors_map_to_exit_codes() {
+    let h = start().await;
+    let client = h.client();
+    client.create_wallet("ops", PASSWORD, false).await.unwrap();
+
+    let duplicate = client.create_wallet("ops", PASSWORD, false).await.unwrap_err();
+    assert_eq!(duplicate.exit_code, EXIT_CONFLICT, "{}", duplicate);
+    assert!(duplicate.to_string().contains("WALLET_EXISTS"), "{}", duplicate);
+
+    let weak = client.create_wallet("weak", "short", false).await.unwrap_err();
+    assert_eq!(weak.exit_code, EXIT_USAGE, "{}", weak);
+
+    let wrong_password = client.balance("ops", "eth", "Wr0ng!Password#1").await.unwrap_err();
+    assert_eq!(wrong_password.exit_code, EXIT_AUTH, "{}", wrong_password);
+
+    let missing = client.balance("ghost", "eth", PASSWORD).await.unwrap_err();
+    assert_eq!(missing.exit_code, EXIT_NOT_FOUND, "{}", missing);
+
+    let bad_amount = client.send("ops", TO, "lots", "eth", PASSWORD).await.unwrap_err();
+    assert_eq!(bad_amount.exit_code, EXIT_USAGE, "{}", bad_amount);
+    assert_eq!(h.sent(), 0);
+
+    let rejected = ApiClient::new(&h.url(), "not-the-admin-key").unwrap().list_wallets().await.unwrap_err();
+    assert_eq!(rejected.exit_code, EXIT_AUTH, "{}", rejected);
+
+    // 端口上没有服务
+    let closed = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    };
+    let unreachable = ApiClient::new(&format!("http://{}", closed), API_KEY).unwrap().list_wallets().await.unwrap_err();
+    assert_eq!(unreachable.exit_code, EXIT_FAILURE, "{}", unreachable);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_api_key_comes_from_environment() {
+    let h = start().await;
+
+    std::env::remove_var("API_KEY");
+    let missing = ApiClient::from_env(&h.url()).err().unwrap();
+    assert_eq!(missing.exit_code, EXIT_USAGE, "{}", missing);
+
+    std::env::set_var("API_KEY", API_KEY);
+    let client = ApiClient::from_env(&h.url()).unwrap();
+    std::env::remove_var("API_KEY");
+    assert!(client.list_wallets().await.unwrap().wallets.is_empty());
+}
+
+#[test]
+fn test_exit_code_for_status() {
+    assert_eq!(exit_code_for_status(200), 0);
+    assert_eq!(exit_code_for_status(202), 0);
+    assert_eq!(exit_code_for_status(400), EXIT_USAGE);
+    assert_eq!(exit_code_for_status(422), EXIT_USAGE);
+    assert_eq!(exit_code_for_status(401), EXIT_AUTH);
+    assert_eq!(exit_code_for_status(403), EXIT_AUTH);
+    assert_eq!(exit_code_for_status(404), EXIT_NOT_FOUND);
+    assert_eq!(exit_code_for_status(409), EXIT_CONFLICT);
+    assert_eq!(exit_code_for_status(429), EXIT_FAILURE);
+    assert_eq!(exit_code_for_status(500), EXIT_FAILURE);
+    assert_eq!(exit_code_for_status(503), EXIT_FAILURE);
+}
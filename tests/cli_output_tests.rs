@@ -0,0 +1,140 @@
+//! wallet-cli 的 `--format json` 输出与退出码：stdout 只有一行可反序列化的结果，
+//! 诊断信息都在 stderr
+
+mod util;
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Eip1559TransactionRequest};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use defi_hot_wallet::cli::output::{
+    HelpText, MnemonicResult, WalletCreated, WalletList, EXIT_FAILURE, EXIT_USAGE,
+};
+
+const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const TO: &str = "0x000000000000000000000000000000000000dEaD";
+
+struct Run {
+    code: i32,
+    stdout: String,
+    stderr: String,
+}
+
+fn wallet_cli(args: &[&str], envs: &[(&str, &str)]) -> Run {
+    util::set_test_env();
+    let mut cmd = cargo_bin_cmd!("wallet-cli");
+    cmd.arg("--json").args(args).env_remove("WALLET_PASSWORD").env_remove("RUST_LOG");
+    for (k, v) in envs {
+        cmd.env(k, v);
+    }
+    let output = cmd.output().unwrap();
+    Run {
+        code: output.status.code().unwrap(),
+        stdout: String::from_utf8(output.stdout).unwrap(),
+        stderr: String::from_utf8(output.stderr).unwrap(),
+    }
+}
+
+/// 成功：退出码 0，stdout 恰好一行 JSON
+fn parse<T: DeserializeOwned>(run: &Run) -> T {
+    assert_eq!(run.code, 0, "stderr: {}", run.stderr);
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    assert_eq!(lines.len(), 1, "stdout: {}", run.stdout);
+    serde_json::from_str(lines[0]).unwrap()
+}
+
+/// 失败：stdout 为空，stderr 最后一行是带退出码的 JSON 错误
+fn failed(run: &Run, code: i32) -> String {
+    assert_eq!(run.code, code, "stdout: {} stderr: {}", run.stdout, run.stderr);
+    assert!(run.stdout.is_empty(), "stdout: {}", run.stdout);
+    let error: Value = serde_json::from_str(run.stderr.lines().last().unwrap()).unwrap();
+    assert_eq!(error["exit_code"], code);
+    error["error"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn test_json_results_deserialize() {
+    let created: WalletCreated = parse(&wallet_cli(&["create", "--name", "json-wallet"], &[]));
+    assert_eq!(created.name, "json-wallet");
+    assert_eq!(created.network, "eth");
+    assert!(created.address.starts_with("0x"));
+    assert_eq!(created.output, None);
+
+    // 每次调用都是新进程，内存中没有wallet
+    let list: WalletList = parse(&wallet_cli(&["list"], &[]));
+    assert!(list.wallets.is_empty());
+
+    let mnemonic: MnemonicResult = parse(&wallet_cli(
+        &["generate-mnemonic"],
+        &[("ALLOW_PLAINTEXT_MNEMONIC", "1"), ("ALLOW_PLAINTEXT_MNEMONIC_CONFIRM", "1")],
+    ));
+    assert_eq!(mnemonic.word_count, 12);
+    assert_eq!(mnemonic.mnemonic.unwrap().split_whitespace().count(), 12);
+
+    let help: HelpText = parse(&wallet_cli(&["help"], &[]));
+    assert!(help.usage.contains("Exit codes"));
+
+    let signer: LocalWallet = KEY.parse().unwrap();
+    let tx: TypedTransaction = Eip1559TransactionRequest::new()
+        .to(Address::repeat_byte(0x42))
+        .value(5u64)
+        .gas(21_000u64)
+        .max_fee_per_gas(2_000_000_000u64)
+        .max_priority_fee_per_gas(1_000_000_000u64)
+        .nonce(3u64)
+        .chain_id(1u64)
+        .into();
+    let signature = signer.clone().with_chain_id(1u64).sign_transaction_sync(&tx).unwrap();
+    let raw = format!("0x{}", hex::encode(tx.rlp_signed(&signature)));
+    let decoded: Value = parse(&wallet_cli(&["decode-tx", "--raw", &raw], &[]));
+    assert_eq!(decoded["tx_type"], "eip1559");
+    assert_eq!(decoded["nonce"], "3");
+    assert_eq!(decoded["from"], format!("{:?}", signer.address()).as_str());
+}
+
+#[test]
+fn test_text_mode_keeps_stdout_for_results() {
+    util::set_test_env();
+    let output = cargo_bin_cmd!("wallet-cli")
+        .args(["--quiet", "generate-mnemonic"])
+        .env_remove("ALLOW_PLAINTEXT_MNEMONIC")
+        .env_remove("MNEMONIC_EXPORT_KEY")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    // 助记词未显示时 stdout 为空；--quiet 也省略了导出提示
+    assert!(output.stdout.is_empty());
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("MNEMONIC_EXPORT_KEY"));
+
+    let run = wallet_cli(&["generate-mnemonic"], &[("ALLOW_PLAINTEXT_MNEMONIC", "0")]);
+    let hidden: MnemonicResult = parse(&run);
+    assert_eq!(hidden.mnemonic, None);
+    assert!(run.stderr.contains("MNEMONIC_EXPORT_KEY"));
+}
+
+#[test]
+fn test_exit_codes() {
+    // RPC 不可用：操作失败
+    let run = wallet_cli(
+        &["transfer", "--name", "w", "--to", TO, "--amount", "0.1eth", "--yes"],
+        &[("WALLET_PASSWORD", "pw")],
+    );
+    failed(&run, EXIT_FAILURE);
+    let run = wallet_cli(&["info", "--name", "missing"], &[]);
+    assert!(failed(&run, EXIT_FAILURE).contains("missing"));
+    let run = wallet_cli(&["bridge", "--name", "w", "--from-chain", "eth", "--to-chain", "polygon", "--token", "ETH", "--amount", "1"], &[]);
+    failed(&run, EXIT_FAILURE);
+
+    // 配置/参数错误
+    let run = wallet_cli(&["transfer", "--name", "w", "--to", TO, "--amount", "0.1eth", "--yes"], &[]);
+    assert!(failed(&run, EXIT_USAGE).contains("WALLET_PASSWORD"));
+    let run = wallet_cli(&["transfer", "--name", "w", "--to", TO, "--amount", "1"], &[("WALLET_PASSWORD", "pw")]);
+    failed(&run, EXIT_USAGE);
+    let run = wallet_cli(&["balance", "--name", "w", "--network", "dogecoin"], &[("WALLET_PASSWORD", "pw")]);
+    assert!(failed(&run, EXIT_USAGE).contains("dogecoin"));
+    let run = wallet_cli(&["decode-tx", "--raw", "0x02f86b0180"], &[]);
+    failed(&run, EXIT_USAGE);
+}
@@ -1,5 +1,5 @@
 use clap::Parser;
-use defi_hot_wallet::cli::{Cli, Commands};
+use defi_hot_wallet::cli::{AddressBookCommand, Cli, Commands};
 use std::process::Command;
 
 #[test]
@@ -178,3 +178,73 @@ fn test_cli_parse_missing_required_arg() {
     let result = Cli::try_parse_from(args);
     assert!(result.is_err());
 }
+
+#[test]
+fn test_cli_parse_keystore_commands() {
+    let args = vec!["hot_wallet", "import-keystore", "--name", "imported", "--file", "keystore.json"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::ImportKeystore { name, file } => {
+            assert_eq!(name, "imported");
+            assert_eq!(file.to_str(), Some("keystore.json"));
+        }
+        _ => panic!("Expected ImportKeystore command"),
+    }
+
+    let args = vec!["hot_wallet", "export-keystore", "--name", "imported", "--output", "out.json"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::ExportKeystore { name, output } => {
+            assert_eq!(name, "imported");
+            assert_eq!(output.to_str(), Some("out.json"));
+        }
+        _ => panic!("Expected ExportKeystore command"),
+    }
+
+    // keystore 文件参数必填
+    assert!(Cli::try_parse_from(vec!["hot_wallet", "import-keystore", "--name", "imported"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_btc_descriptor() {
+    match Cli::try_parse_from(vec!["hot_wallet", "btc-descriptor", "--name", "cold"]).unwrap().command {
+        Commands::BtcDescriptor { name, address_type, account } => {
+            assert_eq!(name, "cold");
+            assert_eq!(address_type, "segwit");
+            assert_eq!(account, 0);
+        }
+        _ => panic!("Expected BtcDescriptor command"),
+    }
+
+    let args = vec!["hot_wallet", "btc-descriptor", "--name", "cold", "--type", "taproot", "--account", "1"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::BtcDescriptor { address_type, account, .. } => {
+            assert_eq!(address_type, "taproot");
+            assert_eq!(account, 1);
+        }
+        _ => panic!("Expected BtcDescriptor command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_address_book_commands() {
+    let args = vec!["hot_wallet", "address-book", "import", "--wallet", "treasury", "--file", "book.csv", "--dry-run"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::AddressBook(AddressBookCommand::Import { wallet, file, dry_run, strict }) => {
+            assert_eq!(wallet, "treasury");
+            assert_eq!(file.to_str(), Some("book.csv"));
+            assert!(dry_run);
+            assert!(!strict);
+        }
+        _ => panic!("Expected address-book import command"),
+    }
+
+    let args = vec!["hot_wallet", "address-book", "export", "--wallet", "treasury"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::AddressBook(AddressBookCommand::Export { wallet, output }) => {
+            assert_eq!(wallet, "treasury");
+            assert!(output.is_none());
+        }
+        _ => panic!("Expected address-book export command"),
+    }
+
+    assert!(Cli::try_parse_from(vec!["hot_wallet", "address-book", "import", "--wallet", "treasury"]).is_err());
+}
@@ -109,7 +109,7 @@ async fn concurrent_send_advances_nonce_by_count() {
 
     // Derive the from_address from the injected test master key
     let from_address =
-        WalletManager::derive_address(&wm, &secret_master, "eth").expect("derive address");
+        WalletManager::derive_address(&wm, &secret_master, "eth", None).expect("derive address");
 
     let concurrency = 8usize;
     let mut handles: Vec<tokio::task::JoinHandle<String>> = Vec::new();
@@ -8,6 +8,7 @@ fn test_env_config_load_with_values() {
         database_url: "sqlite::memory:".to_string(),
         max_connections: Some(5),
         connection_timeout_seconds: Some(30),
+        read_database_url: None,
     };
     let blockchain = BlockchainConfig { networks: HashMap::new() };
     let cfg = WalletConfig {
@@ -17,6 +18,27 @@ fn test_env_config_load_with_values() {
         multi_sig_threshold: 1,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
 
     assert_eq!(cfg.storage.database_url, "sqlite::memory:");
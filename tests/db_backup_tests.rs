@@ -0,0 +1,291 @@
+//! 数据库自动备份：备份 → validate → 清理 全流程，以及 admin 接口
+//!
+//! 全部使用临时目录中的 filesystem sink，不依赖外部存储。
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use axum_test::TestServer;
+use chrono::{TimeZone, Utc};
+use serde_json::Value;
+use tempfile::TempDir;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{BackupConfig, BackupSinkConfig, StorageConfig, WalletConfig};
+use defi_hot_wallet::monitoring::WalletMetrics;
+use defi_hot_wallet::ops::db_backup::{crypto, BackupScheduler, BackupSink, FilesystemSink};
+use defi_hot_wallet::ops::maintenance::MaintenanceMode;
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{NewBackupRecord, WalletStorage, BACKUP_FAILED, BACKUP_VERIFIED};
+
+const KEY: [u8; 32] = [0x42; 32];
+const API_KEY: &str = "db-backup-test-key-0123456789";
+/// base64 of `KEY`; storage also needs it for audit log MACs
+const KEY_B64: &str = "QkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkI=";
+
+struct Fixture {
+    _dir: TempDir,
+    backup_dir: std::path::PathBuf,
+    storage: Arc<WalletStorage>,
+    sink: Arc<FilesystemSink>,
+    maintenance: Arc<MaintenanceMode>,
+}
+
+impl Fixture {
+    async fn new() -> Self {
+        std::env::set_var("WALLET_ENC_KEY", KEY_B64);
+        let dir = TempDir::new().unwrap();
+        let backup_dir = dir.path().join("backups");
+        let db_url = format!("sqlite://{}?mode=rwc", dir.path().join("wallets.db").display());
+        let storage = Arc::new(WalletStorage::new_with_url(&db_url).await.unwrap());
+        storage.store_wallet("backup_me", b"encrypted-wallet-blob", false).await.unwrap();
+        Self {
+            sink: Arc::new(FilesystemSink::new(backup_dir.clone())),
+            _dir: dir,
+            backup_dir,
+            storage,
+            maintenance: Arc::new(MaintenanceMode::new()),
+        }
+    }
+
+    fn scheduler(&self, keep_last: usize, keep_daily: usize) -> BackupScheduler {
+        let config = BackupConfig {
+            enabled: true,
+            sink: BackupSinkConfig::Filesystem { directory: self.backup_dir.clone() },
+            keep_last,
+            keep_daily,
+            ..Default::default()
+        };
+        BackupScheduler::new(
+            config,
+            self.storage.clone(),
+            self.sink.clone(),
+            self.maintenance.clone(),
+            Arc::new(WalletMetrics::new().unwrap()),
+        )
+        .with_key(KEY)
+    }
+
+    fn stored_objects(&self) -> BTreeSet<String> {
+        match std::fs::read_dir(&self.backup_dir) {
+            Ok(entries) => entries.map(|e| e.unwrap().file_name().to_string_lossy().into_owned()).collect(),
+            Err(_) => BTreeSet::new(),
+        }
+    }
+}
+
+async fn decrypt_file(path: &Path) -> Vec<u8> {
+    let mut input = tokio::fs::File::open(path).await.unwrap();
+    let mut out = Vec::new();
+    crypto::decrypt_stream(&KEY, &mut input, &mut out).await.unwrap();
+    out
+}
+
+#[tokio::test]
+async fn test_backup_verify_prune_cycle() {
+    let fx = Fixture::new().await;
+    let scheduler = fx.scheduler(2, 0);
+
+    let mut keys = Vec::new();
+    for _ in 0..3 {
+        let report = scheduler.run_once().await;
+        assert_eq!(report.status, BACKUP_VERIFIED, "{:?}", report.error);
+        assert!(report.id.is_some());
+        assert!(report.size_bytes > 0);
+        keys.push(report.object_key);
+    }
+
+    // keep_last = 2: the oldest backup is gone, the two newest remain
+    let expected: BTreeSet<String> = keys[1..].iter().cloned().collect();
+    assert_eq!(fx.stored_objects(), expected);
+
+    let history = fx.storage.backup_history(10).await.unwrap();
+    assert_eq!(history.len(), 3);
+    assert!(history.iter().all(|b| b.status == BACKUP_VERIFIED && b.sha256.is_some()));
+    let pruned: Vec<_> = history.iter().filter(|b| b.pruned_at.is_some()).map(|b| &b.object_key).collect();
+    assert_eq!(pruned, vec![&keys[0]]);
+
+    // the stored object is ciphertext of a complete SQLite database
+    let stored = fx.backup_dir.join(&keys[2]);
+    let raw = std::fs::read(&stored).unwrap();
+    assert!(raw.starts_with(crypto::MAGIC));
+    assert!(!raw.windows(b"backup_me".len()).any(|w| w == b"backup_me"));
+    let plain = decrypt_file(&stored).await;
+    assert!(plain.starts_with(b"SQLite format 3\0"));
+    assert!(plain.windows(b"backup_me".len()).any(|w| w == b"backup_me"));
+
+    // no plaintext scratch files are left behind
+    assert!(!fx.stored_objects().iter().any(|k| k.ends_with(".partial")));
+}
+
+#[tokio::test]
+async fn test_corrupted_backup_is_detected() {
+    let fx = Fixture::new().await;
+    let scheduler = fx.scheduler(10, 0);
+
+    let report = scheduler.run_once().await;
+    assert_eq!(report.status, BACKUP_VERIFIED);
+    scheduler.verify(&report.object_key).await.unwrap();
+
+    let path = fx.backup_dir.join(&report.object_key);
+    let mut raw = std::fs::read(&path).unwrap();
+    let mid = raw.len() / 2;
+    raw[mid] ^= 0x20;
+    std::fs::write(&path, &raw).unwrap();
+    assert!(scheduler.verify(&report.object_key).await.is_err());
+
+    // correctly encrypted, but not a database: fails the integrity check instead
+    let garbage = fx.backup_dir.join("garbage.src");
+    {
+        let mut out = tokio::fs::File::create(&garbage).await.unwrap();
+        let filler = vec![0xA5u8; 8192];
+        crypto::encrypt_stream(&KEY, &mut filler.as_slice(), &mut out).await.unwrap();
+    }
+    fx.sink.put("not-a-db.db.enc", &garbage).await.unwrap();
+    assert!(scheduler.verify("not-a-db.db.enc").await.is_err());
+
+    assert!(scheduler.verify("missing.db.enc").await.is_err());
+}
+
+#[tokio::test]
+async fn test_unverifiable_backup_is_recorded_as_failed() {
+    let fx = Fixture::new().await;
+    // a different key at verification time is indistinguishable from corruption
+    let scheduler = fx.scheduler(10, 0);
+    let wrong_key = BackupScheduler::new(
+        scheduler.config().clone(),
+        fx.storage.clone(),
+        fx.sink.clone(),
+        fx.maintenance.clone(),
+        Arc::new(WalletMetrics::new().unwrap()),
+    )
+    .with_key([0x43; 32]);
+
+    let report = scheduler.run_once().await;
+    assert!(wrong_key.verify(&report.object_key).await.is_err());
+
+    // write fails outright: the sink directory is a file
+    std::fs::remove_dir_all(&fx.backup_dir).unwrap();
+    std::fs::write(&fx.backup_dir, b"not a directory").unwrap();
+    let failed = scheduler.run_once().await;
+    assert_eq!(failed.status, BACKUP_FAILED);
+    assert!(failed.error.is_some());
+
+    let history = fx.storage.backup_history(10).await.unwrap();
+    assert_eq!(history[0].status, BACKUP_FAILED);
+    assert_eq!(history[0].error, failed.error);
+
+    // failed backups are never retained
+    std::fs::remove_file(&fx.backup_dir).unwrap();
+    std::fs::create_dir_all(&fx.backup_dir).unwrap();
+    assert_eq!(scheduler.prune().await.unwrap(), 1);
+    let history = fx.storage.backup_history(10).await.unwrap();
+    assert!(history[0].pruned_at.is_some());
+    assert!(history[1].pruned_at.is_none());
+}
+
+#[tokio::test]
+async fn test_prune_keeps_exactly_configured_set() {
+    let fx = Fixture::new().await;
+    std::fs::create_dir_all(&fx.backup_dir).unwrap();
+
+    // four days, two backups per day (06:00 and 18:00), plus one failed backup
+    let day0 = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap().timestamp();
+    let mut rows = Vec::new();
+    for day in 0..4 {
+        for hour in [6, 18] {
+            rows.push((format!("d{}-h{:02}.db.enc", day, hour), BACKUP_VERIFIED, day0 + day * 86_400 + hour * 3600));
+        }
+    }
+    rows.push(("failed.db.enc".to_string(), BACKUP_FAILED, day0 + 3 * 86_400 + 20 * 3600));
+    for (key, status, created_at) in &rows {
+        std::fs::write(fx.backup_dir.join(key), b"x").unwrap();
+        fx.storage
+            .record_backup(&NewBackupRecord {
+                object_key: key,
+                sink: "filesystem",
+                status,
+                size_bytes: 1,
+                sha256: None,
+                error: None,
+                created_at: *created_at,
+            })
+            .await
+            .unwrap();
+    }
+
+    // keep_last = 3: d3-h18, d3-h06, d2-h18
+    // keep_daily = 3: newest of days 3, 2, 1 -> d3-h18, d2-h18, d1-h18
+    let pruned = fx.scheduler(3, 3).prune().await.unwrap();
+    let expected: BTreeSet<String> =
+        ["d3-h18.db.enc", "d3-h06.db.enc", "d2-h18.db.enc", "d1-h18.db.enc"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(fx.stored_objects(), expected);
+    assert_eq!(pruned, rows.len() - expected.len());
+
+    let history = fx.storage.backup_history(100).await.unwrap();
+    for record in &history {
+        assert_eq!(record.pruned_at.is_none(), expected.contains(&record.object_key), "{}", record.object_key);
+    }
+
+    // pruning is idempotent
+    assert_eq!(fx.scheduler(3, 3).prune().await.unwrap(), 0);
+    assert_eq!(fx.stored_objects(), expected);
+}
+
+#[tokio::test]
+async fn test_maintenance_mode_skips_backup() {
+    let fx = Fixture::new().await;
+    fx.maintenance.enable("restore in progress");
+    let report = fx.scheduler(1, 0).run_once().await;
+    assert!(report.skipped);
+    assert!(fx.storage.backup_history(10).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_admin_backup_endpoints() {
+    let dir = TempDir::new().unwrap();
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: format!("sqlite://{}?mode=rwc", dir.path().join("wallets.db").display()),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        backups: BackupConfig {
+            sink: BackupSinkConfig::Filesystem { directory: dir.path().join("backups") },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    // the test constructor installs the all-zero key, which backups refuse outside test builds
+    std::env::set_var("WALLET_ENC_KEY", KEY_B64);
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    app.get("/api/admin/backups").await.assert_status_unauthorized();
+    app.post("/api/admin/backups/run").await.assert_status_unauthorized();
+
+    let res = app.post("/api/admin/backups/run").add_header("Authorization", API_KEY).await;
+    res.assert_status_ok();
+    let run: Value = res.json();
+    assert_eq!(run["status"], BACKUP_VERIFIED, "{}", run);
+    let object_key = run["object_key"].as_str().unwrap().to_string();
+    assert!(dir.path().join("backups").join(&object_key).exists());
+
+    let res = app.get("/api/admin/backups?limit=10").add_header("Authorization", API_KEY).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["enabled"], false);
+    assert_eq!(body["backups"].as_array().unwrap().len(), 1);
+    assert_eq!(body["backups"][0]["object_key"], object_key);
+    assert_eq!(body["backups"][0]["sink"], "filesystem");
+}
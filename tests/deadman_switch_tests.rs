@@ -0,0 +1,355 @@
+//! dead-man's switch：签到重置计时、警告时间表、到期只向受益人归集
+
+mod util;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use chrono::Duration;
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, H256, U256};
+use ethers::utils::rlp::Rlp;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::blockchain::gas_oracle::{min_transfer_cost, GasOracle};
+use defi_hot_wallet::core::clock::FixedClock;
+use defi_hot_wallet::core::config::{SecurityConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::core::ids::SequentialIds;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::ops::deadman::DeadmanEvaluator;
+use defi_hot_wallet::storage::journal_events::{DEADMAN_SWITCH_TRIGGERED, DEADMAN_SWITCH_WARNING};
+use defi_hot_wallet::storage::{WalletStorage, DEADMAN_ARMED, DEADMAN_TRIGGERED};
+
+const API_KEY: &str = "deadman-switch-admin-key";
+const OWNER_TOKEN: &str = "deadman-owner-token";
+const WALLET: &str = "estate";
+const PASSWORD: &str = "Est4te!Vault#2024";
+const BENEFICIARY: &str = "0x00000000000000000000000000000000000000b0";
+const OTHER: &str = "0x00000000000000000000000000000000000000c0";
+const NOW: i64 = 1_700_000_000;
+const DAY: i64 = 86_400;
+const GWEI: u64 = 1_000_000_000;
+const BALANCE: u64 = 2_000_000_000_000_000_000;
+
+/// 节点：记录广播的原始transaction
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    raw: Mutex<Vec<Bytes>>,
+}
+
+impl MockChain {
+    /// 已广播transaction的 (to, value)
+    fn transfers(&self) -> Vec<(Address, U256)> {
+        self.raw
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|raw| {
+                let (tx, _) = TypedTransaction::decode_signed(&Rlp::new(raw)).unwrap();
+                (*tx.to_addr().unwrap(), *tx.value().unwrap())
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        if tx.gas().is_none() {
+            tx.set_gas(21_000u64);
+        }
+        if tx.gas_price().is_none() {
+            tx.set_gas_price(3 * GWEI);
+        }
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        let hash = H256::from(ethers::utils::keccak256(&raw));
+        self.raw.lock().unwrap().push(raw);
+        Ok(hash)
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+}
+
+/// 所有address余额相同
+struct FixedOracle {
+    balance: U256,
+}
+
+#[async_trait]
+impl GasOracle for FixedOracle {
+    async fn gas_price(&self, _network: &str) -> Result<U256, WalletError> {
+        Ok(U256::from(GWEI))
+    }
+
+    async fn native_balance(&self, _network: &str, _address: &str) -> Result<U256, WalletError> {
+        Ok(self.balance)
+    }
+
+    fn supports(&self, network: &str) -> bool {
+        network == "eth"
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    chain: Arc<MockChain>,
+    clock: Arc<FixedClock>,
+    storage: Arc<WalletStorage>,
+    evaluator: DeadmanEvaluator,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    build_with(util::memory_config()).await
+}
+
+async fn build_with(config: WalletConfig) -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let clock = Arc::new(FixedClock::at(NOW));
+    let chain = Arc::new(MockChain::default());
+    let server = util::test_server_with_sources(
+        dir.path(),
+        config,
+        Some(API_KEY),
+        clock.clone(),
+        Arc::new(SequentialIds::new()),
+    )
+    .await
+    .with_broadcast_chain(chain.clone())
+    .with_gas_oracle(Arc::new(FixedOracle { balance: U256::from(BALANCE) }));
+
+    let user_id = util::sign_in(&server, "deadman-owner@example.com", OWNER_TOKEN).await;
+    util::create_wallet(&server, WALLET, PASSWORD).await;
+    let address = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+    server.user_db.link_wallet(&user_id, WALLET, &format!("{:#x}", address), None).await.unwrap();
+
+    let storage = server.storage.clone();
+    let evaluator = server.deadman_evaluator();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, chain, clock, storage, evaluator, _dir: dir }
+}
+
+impl Harness {
+    /// 30 天无签到触发，提前 7 天和 1 天警告
+    async fn arm(&self) -> Value {
+        let res = self
+            .app
+            .post(&format!("/api/wallets/{}/deadman", WALLET))
+            .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+            .json(&json!({
+                "beneficiary": BENEFICIARY,
+                "networks": ["eth"],
+                "inactivity_secs": 30 * DAY,
+                "warning_secs": [DAY, 7 * DAY],
+                "password": PASSWORD,
+            }))
+            .await;
+        res.assert_status_ok();
+        res.json()
+    }
+
+    async fn checkin(&self) -> axum_test::TestResponse {
+        self.app
+            .post(&format!("/api/wallets/{}/deadman/checkin", WALLET))
+            .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+            .await
+    }
+
+    async fn last_checkin_at(&self) -> i64 {
+        self.storage.deadman_policy(WALLET).await.unwrap().unwrap().last_checkin_at
+    }
+
+    async fn journal_count(&self, event_type: &str) -> usize {
+        let events = self.storage.journal_events(0, 100).await.unwrap();
+        events.iter().filter(|e| e.event_type == event_type).count()
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_checkins_restart_the_timer() {
+    let h = build().await;
+    let body = h.arm().await;
+    assert_eq!(body["status"], "armed");
+    assert_eq!(body["warning_secs"], json!([7 * DAY, DAY]));
+    assert_eq!(body["deadline"], NOW + 30 * DAY);
+    assert!(body.get("capability").is_none());
+
+    h.clock.advance(Duration::days(3));
+    let res = h.checkin().await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["deadline"], NOW + 33 * DAY);
+
+    // 服务端sign的发送同样算签到
+    h.clock.advance(Duration::days(2));
+    h.app
+        .post(&format!("/api/wallets/{}/send", WALLET))
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .json(&json!({ "to": OTHER, "amount": "0.1", "network": "eth", "password": PASSWORD }))
+        .await
+        .assert_status_ok();
+    assert_eq!(h.last_checkin_at().await, NOW + 5 * DAY);
+
+    // 其他人无权查看或签到
+    h.app.get(&format!("/api/wallets/{}/deadman", WALLET)).await.assert_status_unauthorized();
+    let res = h.app.get(&format!("/api/wallets/{}/deadman", WALLET)).add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["beneficiary"], BENEFICIARY);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_warnings_follow_the_schedule() {
+    let h = build().await;
+    h.arm().await;
+
+    assert_eq!(h.evaluator.run_once().await.warnings, 0);
+    h.clock.advance(Duration::days(23));
+    assert_eq!(h.evaluator.run_once().await.warnings, 1);
+    // 同一警告只发一次
+    assert_eq!(h.evaluator.run_once().await.warnings, 0);
+    h.clock.advance(Duration::days(6));
+    assert_eq!(h.evaluator.run_once().await.warnings, 1);
+    assert_eq!(h.journal_count(DEADMAN_SWITCH_WARNING).await, 2);
+
+    // 签到后时间表重新开始
+    h.checkin().await.assert_status_ok();
+    let report = h.evaluator.run_once().await;
+    assert_eq!((report.warnings, report.triggered), (0, 0));
+    h.clock.advance(Duration::days(23));
+    assert_eq!(h.evaluator.run_once().await.warnings, 1);
+    assert_eq!(h.journal_count(DEADMAN_SWITCH_WARNING).await, 3);
+    assert!(h.chain.transfers().is_empty());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_trigger_sweeps_only_to_beneficiary() {
+    let h = build().await;
+    h.arm().await;
+
+    h.clock.advance(Duration::days(30));
+    let report = h.evaluator.run_once().await;
+    assert_eq!(report.triggered, 1);
+    assert_eq!(report.sweeps.len(), 1);
+    assert_eq!(report.sweeps[0].status, "sent");
+
+    let expected = U256::from(BALANCE) - min_transfer_cost(U256::from(GWEI));
+    assert_eq!(h.chain.transfers(), vec![(BENEFICIARY.parse().unwrap(), expected)]);
+    assert_eq!(h.journal_count(DEADMAN_SWITCH_TRIGGERED).await, 1);
+
+    let policy = h.storage.deadman_policy(WALLET).await.unwrap().unwrap();
+    assert_eq!(policy.status, DEADMAN_TRIGGERED);
+    assert!(policy.pending_networks.is_empty());
+    let audit = h.storage.get_audit_logs(Some(WALLET)).await.unwrap();
+    assert!(audit.iter().any(|a| a.action == "deadman.triggered"));
+    assert!(audit.iter().any(|a| a.action == "deadman.sweep"));
+
+    // 已触发：不再接受签到，也不会再次归集
+    let res = h.checkin().await;
+    res.assert_status(axum::http::StatusCode::CONFLICT);
+    assert_eq!(res.json::<Value>()["error"]["code"], "DEADMAN_TRIGGERED");
+    let report = h.evaluator.run_once().await;
+    assert_eq!((report.triggered, report.sweeps.len()), (0, 0));
+    assert_eq!(h.chain.transfers().len(), 1);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_feature_flag_pauses_the_switch() {
+    let h = build().await;
+    h.arm().await;
+    let set_flag = |enabled: Option<bool>| {
+        h.app
+            .put("/api/admin/flags")
+            .add_header("X-API-KEY", API_KEY)
+            .json(&json!({ "flag": "deadman_switch", "wallet_id": WALLET, "enabled": enabled }))
+    };
+    set_flag(Some(false)).await.assert_status_ok();
+
+    h.clock.advance(Duration::days(30));
+    let report = h.evaluator.run_once().await;
+    assert_eq!((report.disabled, report.triggered), (1, 0));
+    assert!(h.chain.transfers().is_empty());
+    assert_eq!(h.storage.deadman_policy(WALLET).await.unwrap().unwrap().status, DEADMAN_ARMED);
+
+    // 删除规则后回到默认值（开启）
+    set_flag(None).await.assert_status_ok();
+    let report = h.evaluator.run_once().await;
+    assert_eq!((report.disabled, report.triggered), (0, 1));
+    assert_eq!(h.chain.transfers().len(), 1);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_security_section_turns_the_switch_off_and_lowers_the_minimum() {
+    let doc: toml::Value =
+        toml::from_str("[security.deadman]\nenabled = false\nmin_inactivity_secs = 3600\n").unwrap();
+    let mut config = util::memory_config();
+    config.security = doc["security"].clone().try_into::<SecurityConfig>().unwrap();
+    let h = build_with(config).await;
+
+    // 低于默认的一天，但不低于配置的下限
+    let arm = |inactivity_secs: i64| {
+        h.app
+            .post(&format!("/api/wallets/{}/deadman", WALLET))
+            .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+            .json(&json!({
+                "beneficiary": BENEFICIARY,
+                "networks": ["eth"],
+                "inactivity_secs": inactivity_secs,
+                "warning_secs": [],
+                "password": PASSWORD,
+            }))
+    };
+    arm(1800).await.assert_status_bad_request();
+    arm(2 * 3600).await.assert_status_ok();
+
+    // 关闭后已武装的策略不会触发
+    h.clock.advance(Duration::days(1));
+    let report = h.evaluator.run_once().await;
+    assert_eq!((report.disabled, report.triggered), (1, 0));
+    assert!(h.chain.transfers().is_empty());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_capability_is_bound_to_beneficiary() {
+    let h = build().await;
+    h.arm().await;
+
+    let policy = h.storage.deadman_policy(WALLET).await.unwrap().unwrap();
+    assert!(matches!(policy.capability.open(OTHER, &policy.networks), Err(WalletError::SecurityError(_))));
+    let signer = policy.capability.open(BENEFICIARY, &policy.networks).unwrap();
+    assert_eq!(signer.destination(), BENEFICIARY.parse::<Address>().unwrap());
+
+    // 取消需要正确的Password
+    let res = h
+        .app
+        .delete(&format!("/api/wallets/{}/deadman", WALLET))
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .json(&json!({ "password": "wrong-password" }))
+        .await;
+    res.assert_status_unauthorized();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_PASSWORD");
+    h.app
+        .delete(&format!("/api/wallets/{}/deadman", WALLET))
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .json(&json!({ "password": PASSWORD }))
+        .await
+        .assert_status_ok();
+    assert!(h.storage.deadman_policy(WALLET).await.unwrap().is_none());
+    h.checkin().await.assert_status_not_found();
+}
@@ -0,0 +1,301 @@
+//! 委托会话：预算并发扣减、单笔上限、收款方白名单、过期、即时吊销与端点范围
+
+mod util;
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Bytes, H256};
+use serde_json::{json, Value};
+use std::future::IntoFuture;
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::core::clock::FixedClock;
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::core::ids::SequentialIds;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::storage::WalletStorage;
+
+const API_KEY: &str = "delegation-admin-key";
+const OWNER_TOKEN: &str = "delegation-owner-token";
+const WALLET: &str = "rebalancer";
+const OTHER_WALLET: &str = "reserve";
+const PASSWORD: &str = "R3balance!Vault#2024";
+const ALICE: &str = "0x000000000000000000000000000000000000a11c";
+const BOB: &str = "0x000000000000000000000000000000000000b0b0";
+const MALLORY: &str = "0x000000000000000000000000000000000000bad0";
+const NOW: i64 = 1_700_000_000;
+const ETHER: &str = "1000000000000000000";
+
+/// 节点：只记录广播次数
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    broadcasts: Mutex<usize>,
+}
+
+impl MockChain {
+    fn broadcasts(&self) -> usize {
+        *self.broadcasts.lock().unwrap()
+    }
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        *self.broadcasts.lock().unwrap() += 1;
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    chain: Arc<MockChain>,
+    clock: Arc<FixedClock>,
+    storage: Arc<WalletStorage>,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let clock = Arc::new(FixedClock::at(NOW));
+    let chain = Arc::new(MockChain::default());
+    let server = util::test_server_with_sources(
+        dir.path(),
+        util::file_config(dir.path()),
+        Some(API_KEY),
+        clock.clone(),
+        Arc::new(SequentialIds::new()),
+    )
+    .await
+    .with_broadcast_chain(chain.clone());
+
+    let user_id = util::sign_in(&server, "delegation-owner@example.com", OWNER_TOKEN).await;
+    for name in [WALLET, OTHER_WALLET] {
+        util::create_wallet(&server, name, PASSWORD).await;
+        let address = server.wallet_manager.ethereum_signer(name, PASSWORD).await.unwrap().address();
+        server.user_db.link_wallet(&user_id, name, &format!("{:#x}", address), None).await.unwrap();
+    }
+
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, chain, clock, storage, _dir: dir }
+}
+
+impl Harness {
+    /// 1 ETH 总预算、0.6 ETH 单笔上限、1 小时有效
+    async fn delegate(&self, recipients: Option<Vec<&str>>) -> Value {
+        let mut body = json!({
+            "budgets": { "eth": ETHER },
+            "per_tx_cap": "600000000000000000",
+            "expires_in_secs": 3600,
+            "password": PASSWORD,
+        });
+        if let Some(recipients) = recipients {
+            body["recipients"] = json!(recipients);
+        }
+        let res = self
+            .app
+            .post(&format!("/api/wallets/{}/delegations", WALLET))
+            .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+            .json(&body)
+            .await;
+        res.assert_status_ok();
+        res.json()
+    }
+
+    /// 代理会重复完全相同的付款；重复检测由 duplicate_send_tests 覆盖
+    fn send_request(&self, token: &str, wallet: &str, to: &str, amount: &str) -> axum_test::TestRequest {
+        self.app
+            .post(&format!("/api/wallets/{}/send", wallet))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({ "to": to, "amount": amount, "network": "eth", "allow_duplicate": true }))
+    }
+
+    async fn send(&self, token: &str, to: &str, amount: &str) -> axum_test::TestResponse {
+        self.send_request(token, WALLET, to, amount).await
+    }
+
+    async fn delegation(&self, id: &str) -> Value {
+        let res = self
+            .app
+            .get(&format!("/api/wallets/{}/delegations/{}", WALLET, id))
+            .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+            .await;
+        res.assert_status_ok();
+        res.json()
+    }
+}
+
+fn code(res: &axum_test::TestResponse) -> String {
+    res.json::<Value>()["error"]["code"].as_str().unwrap_or_default().to_string()
+}
+
+fn token(delegation: &Value) -> &str {
+    delegation["token"].as_str().unwrap()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_concurrent_sends_cannot_overspend_the_budget() {
+    let h = build().await;
+    let delegation = h.delegate(None).await;
+    assert!(token(&delegation).starts_with("dlg_"));
+    assert_eq!(delegation["budgets"][0]["remaining"], ETHER);
+
+    // 0.6 + 0.5 > 1：只能有一笔成功
+    let (first, second) = tokio::join!(
+        h.send_request(token(&delegation), WALLET, ALICE, "0.6").into_future(),
+        h.send_request(token(&delegation), WALLET, BOB, "0.5").into_future(),
+    );
+    let statuses = [first.status_code(), second.status_code()];
+    assert_eq!(statuses.iter().filter(|s| **s == StatusCode::OK).count(), 1, "{:?}", statuses);
+    let (remaining, rejected) = if first.status_code() == StatusCode::OK {
+        ("400000000000000000", second)
+    } else {
+        ("500000000000000000", first)
+    };
+    rejected.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(code(&rejected), "DELEGATION_BUDGET_EXHAUSTED");
+    assert_eq!(h.chain.broadcasts(), 1);
+
+    let id = delegation["id"].as_str().unwrap();
+    let budget = h.delegation(id).await["budgets"][0].clone();
+    assert_eq!(budget["network"], "eth");
+    assert_eq!(budget["remaining"], remaining);
+
+    // 每笔委托发送都带 delegation id 审计
+    let logs = h.storage.get_audit_logs(Some(WALLET)).await.unwrap();
+    let sends: Vec<Value> = logs
+        .iter()
+        .filter(|l| l.action == "delegation.send")
+        .map(|l| serde_json::from_str(l.details.as_deref().unwrap()).unwrap())
+        .collect();
+    assert_eq!(sends.len(), 1);
+    assert_eq!(sends[0]["delegation_id"], id);
+    assert!(sends[0]["tx_hash"].as_str().unwrap().starts_with("0x"));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_per_tx_cap_and_recipient_allowlist() {
+    let h = build().await;
+    let delegation = h.delegate(Some(vec![ALICE, BOB])).await;
+    let token = token(&delegation);
+
+    let res = h.send(token, ALICE, "0.7").await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(code(&res), "DELEGATION_TX_CAP_EXCEEDED");
+
+    let res = h.send(token, MALLORY, "0.1").await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(code(&res), "DELEGATION_RECIPIENT_DENIED");
+    assert_eq!(h.chain.broadcasts(), 0);
+
+    h.send(token, BOB, "0.1").await.assert_status_ok();
+    assert_eq!(h.chain.broadcasts(), 1);
+
+    // 被拒绝的发送不占用预算
+    let id = delegation["id"].as_str().unwrap();
+    assert_eq!(h.delegation(id).await["budgets"][0]["spent"], "100000000000000000");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_expiry_and_revocation_take_effect_immediately() {
+    let h = build().await;
+    let expiring = h.delegate(None).await;
+    h.send(token(&expiring), ALICE, "0.1").await.assert_status_ok();
+    h.clock.advance(chrono::Duration::seconds(3600));
+    let res = h.send(token(&expiring), ALICE, "0.1").await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(code(&res), "DELEGATION_EXPIRED");
+
+    let delegation = h.delegate(None).await;
+    let id = delegation["id"].as_str().unwrap();
+    h.send(token(&delegation), ALICE, "0.1").await.assert_status_ok();
+    h.app
+        .delete(&format!("/api/wallets/{}/delegations/{}", WALLET, id))
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .await
+        .assert_status_ok();
+    let res = h.send(token(&delegation), ALICE, "0.1").await;
+    res.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(code(&res), "INVALID_TOKEN");
+    assert_eq!(h.chain.broadcasts(), 2);
+
+    // owner 仍可查看：两条都在，已吊销的带 revoked_at
+    let list: Value = h
+        .app
+        .get(&format!("/api/wallets/{}/delegations", WALLET))
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .await
+        .json();
+    let listed = list["delegations"].as_array().unwrap();
+    assert_eq!(listed.len(), 2);
+    assert!(listed.iter().all(|d| d.get("token").is_none()));
+    assert!(h.delegation(id).await["revoked_at"].is_i64());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_delegation_token_cannot_reach_other_endpoints() {
+    let h = build().await;
+    let delegation = h.delegate(None).await;
+    let token = token(&delegation);
+    let bearer = format!("Bearer {}", token);
+
+    // 其他wallet的 send
+    let res = h.send_request(token, OTHER_WALLET, ALICE, "0.1").await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(code(&res), "DELEGATION_SCOPE_DENIED");
+
+    let gets = [
+        format!("/api/wallets/{}/balance?network=eth", WALLET),
+        format!("/api/wallets/{}/history", WALLET),
+        format!("/api/wallets/{}/delegations", WALLET),
+        format!("/api/wallets/{}/tokens", WALLET),
+        format!("/api/wallets/{}/address", WALLET),
+        "/api/wallets".to_string(),
+    ];
+    for path in &gets {
+        let res = h.app.get(path).add_header("Authorization", bearer.clone()).await;
+        let status = res.status_code();
+        assert!(status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN, "{} -> {}", path, status);
+    }
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/delegations", WALLET))
+        .add_header("Authorization", bearer.clone())
+        .json(&json!({
+            "budgets": { "eth": ETHER },
+            "per_tx_cap": ETHER,
+            "expires_in_secs": 60,
+            "password": PASSWORD,
+        }))
+        .await;
+    assert!(matches!(res.status_code(), StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN));
+    let id = delegation["id"].as_str().unwrap();
+    let res =
+        h.app.delete(&format!("/api/wallets/{}/delegations/{}", WALLET, id)).add_header("Authorization", bearer).await;
+    assert!(matches!(res.status_code(), StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN));
+
+    // 仍然有效，只是不能越权
+    h.send(token, ALICE, "0.1").await.assert_status_ok();
+}
@@ -0,0 +1,183 @@
+//! 自定义 BIP32 派生路径：创建时记住wallet的默认路径，sign与address推导保持一致；
+//! `GET /api/wallets/:name/addresses?derivation_path=` 与创建接口的路径校验
+
+mod util;
+
+use axum_test::TestServer;
+use ethers::signers::Signer;
+use serde_json::{json, Value};
+
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::core::config::WalletConfig;
+use defi_hot_wallet::core::wallet_manager::derivation::DerivationPath;
+use defi_hot_wallet::core::wallet_manager::{CreateWalletOptions, WalletManager};
+use defi_hot_wallet::storage::WalletStorage;
+
+const PASSWORD: &str = "Deriv@tion#Path2024";
+const API_KEY: &str = "derivation-path-admin-key";
+const SESSION: &str = "derivation-path-session";
+const ADDRESS: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+
+fn config(database_url: String) -> WalletConfig {
+    let mut config = util::memory_config();
+    config.storage.database_url = database_url;
+    config.security.pbkdf2_iterations = 1_000;
+    config
+}
+
+fn path(raw: &str) -> DerivationPath {
+    DerivationPath::parse(raw).unwrap()
+}
+
+#[tokio::test]
+async fn test_created_wallet_signs_at_its_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display());
+    let manager = WalletManager::new(&config(url.clone())).await.unwrap();
+    let storage = WalletStorage::new_with_url(&url).await.unwrap();
+    let clients = ClientRegistry::new();
+
+    let options = CreateWalletOptions {
+        password: PASSWORD.to_string(),
+        quantum_safe: false,
+        networks: vec!["eth".to_string()],
+        derivation_path: Some(path("m/44'/60'/0'/0/5")),
+        passphrase: None,
+    };
+    let statuses = manager.create_wallet_full("custom", options, &storage, &clients).await.unwrap();
+    let address = statuses[0].address.clone();
+
+    let wallet = manager.get_wallet_by_name("custom").await.unwrap().unwrap();
+    assert_eq!(wallet.info.derivation_path, Some(path("m/44'/60'/0'/0/5")));
+
+    // sign、address查询与补全network都沿用保存的路径
+    let signer = manager.ethereum_signer("custom", PASSWORD).await.unwrap();
+    assert_eq!(format!("{:#x}", signer.address()), address);
+    let from_master = manager.get_ethereum_address_from_master_key("custom", PASSWORD).await.unwrap();
+    assert!(from_master.eq_ignore_ascii_case(&address));
+    let stored = manager.derive_wallet_address("custom", PASSWORD, "eth", None).await.unwrap();
+    assert_eq!(stored, address);
+    let status = manager.initialize_network("custom", "polygon", PASSWORD, &storage, &clients).await.unwrap();
+    assert_eq!(status.address, address);
+
+    // 显式路径覆盖默认路径
+    let other = manager.derive_wallet_address("custom", PASSWORD, "eth", Some(&path("m/44'/60'/0'/0/6"))).await;
+    assert_ne!(other.unwrap(), address);
+
+    // 不带路径的wallet直接使用主密钥
+    let plain = CreateWalletOptions {
+        password: PASSWORD.to_string(),
+        quantum_safe: false,
+        networks: vec!["eth".to_string()],
+        derivation_path: None,
+        passphrase: None,
+    };
+    let statuses = manager.create_wallet_full("plain", plain, &storage, &clients).await.unwrap();
+    let signer = manager.ethereum_signer("plain", PASSWORD).await.unwrap();
+    assert_eq!(format!("{:#x}", signer.address()), statuses[0].address);
+    assert!(manager.get_wallet_by_name("plain").await.unwrap().unwrap().info.derivation_path.is_none());
+}
+
+struct Harness {
+    app: TestServer,
+    server_address: String,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server(dir.path(), config("sqlite::memory:".to_string()), Some(API_KEY)).await;
+    let user_id = util::sign_in(&server, "derive@example.com", SESSION).await;
+    server.wallet_manager.create_wallet("vault", PASSWORD, false).await.unwrap();
+    let server_address = server.wallet_manager.get_ethereum_address_from_master_key("vault", PASSWORD).await.unwrap();
+    server.user_db.link_wallet(&user_id, "vault", &server_address, None).await.unwrap();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, server_address, _dir: dir }
+}
+
+impl Harness {
+    async fn address(&self, derivation_path: Option<&str>, password: Option<&str>) -> axum_test::TestResponse {
+        let mut req =
+            self.app.get("/api/wallets/vault/addresses").add_header("Authorization", format!("Bearer {}", SESSION));
+        if let Some(derivation_path) = derivation_path {
+            req = req.add_query_param("derivation_path", derivation_path);
+        }
+        if let Some(password) = password {
+            req = req.add_header("X-Wallet-Password", password);
+        }
+        req.await
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_get_address_at_requested_path() {
+    let h = build().await;
+
+    let res = h.address(Some("m/44'/60'/0'/0/5"), Some(PASSWORD)).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["network"], "eth");
+    assert_eq!(body["derivation_path"], "m/44'/60'/0'/0/5");
+    let at_five = body["address"].as_str().unwrap().to_string();
+    assert!(!at_five.eq_ignore_ascii_case(&h.server_address));
+
+    // h 记法规范化后是同一路径
+    let body: Value = h.address(Some("m/44h/60h/0h/0/5"), Some(PASSWORD)).await.json();
+    assert_eq!(body["address"], at_five);
+    assert_eq!(body["derivation_path"], "m/44'/60'/0'/0/5");
+
+    let body: Value = h.address(Some("m/44'/60'/0'/0/6"), Some(PASSWORD)).await.json();
+    assert_ne!(body["address"], at_five);
+
+    // 不带路径时返回登记的address，不需要Password
+    let res = h.address(None, None).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert!(body["address"].as_str().unwrap().eq_ignore_ascii_case(&h.server_address));
+    assert!(body.get("derivation_path").is_none());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_get_address_rejects_bad_paths_and_passwords() {
+    let h = build().await;
+
+    for bad in ["m/44/60'/0'/0/0", "m/44'/60'/0'/0/2147483648", "m/44'/60'/0'/x/0", "44'/60'/0'/0/0", "m/44'/60'"] {
+        let res = h.address(Some(bad), Some(PASSWORD)).await;
+        res.assert_status_bad_request();
+        assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_DERIVATION_PATH", "{}", bad);
+    }
+
+    let res = h.address(Some("m/44'/60'/0'/0/5"), None).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "MISSING_PARAMETER");
+
+    let res = h.address(Some("m/44'/60'/0'/0/5"), Some("Wr0ng!Password")).await;
+    res.assert_status_unauthorized();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_PASSWORD");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_create_wallet_validates_path() {
+    let h = build().await;
+
+    let res = h
+        .app
+        .post("/api/wallets")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "name": "hot", "wallet_address": ADDRESS, "derivation_path": "m/44'/60'/0/0/5" }))
+        .await;
+    res.assert_status_bad_request();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "INVALID_DERIVATION_PATH");
+    assert!(body["error"]["message"].as_str().unwrap().contains("must be hardened"));
+
+    h.app
+        .post("/api/wallets")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "name": "hot", "wallet_address": ADDRESS, "derivation_path": "m/44'/60'/0'/0/5" }))
+        .await
+        .assert_status_ok();
+}
@@ -0,0 +1,189 @@
+//! `distributed_locks` 租约与调度器单实例运行测试
+//!
+//! 两个 WalletStorage 句柄指向同一个数据库文件，模拟共享数据库的两个实例。
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use defi_hot_wallet::ops::leases::{spawn_periodic, LeaderLease};
+use defi_hot_wallet::storage::{WalletStorage, WalletStorageTrait};
+
+async fn two_instances() -> (Arc<WalletStorage>, Arc<WalletStorage>, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}", dir.path().join("shared.db").display());
+    let a = WalletStorage::new_with_url(&url).await.unwrap();
+    let b = WalletStorage::new_with_url(&url).await.unwrap();
+    assert_ne!(a.instance_id(), b.instance_id());
+    (Arc::new(a), Arc::new(b), dir)
+}
+
+#[tokio::test]
+async fn test_contention_has_exactly_one_winner() {
+    let (a, b, _dir) = two_instances().await;
+    let ttl = Duration::from_secs(30);
+
+    let (won_a, won_b) = tokio::join!(a.try_acquire_lock("job", ttl), b.try_acquire_lock("job", ttl));
+    let (won_a, won_b) = (won_a.unwrap(), won_b.unwrap());
+    assert!(won_a ^ won_b, "exactly one instance must win (a={}, b={})", won_a, won_b);
+
+    let (winner, loser) = if won_a { (&a, &b) } else { (&b, &a) };
+    // 持有者可重入，其他实例仍被拒绝
+    assert!(winner.try_acquire_lock("job", ttl).await.unwrap());
+    assert!(!loser.try_acquire_lock("job", ttl).await.unwrap());
+    assert!(!loser.renew_lock("job", ttl).await.unwrap());
+    // 只能释放自己的锁
+    assert!(!loser.release_lock("job").await.unwrap());
+
+    let locks = loser.active_locks().await.unwrap();
+    assert_eq!(locks.len(), 1);
+    assert_eq!(locks[0].holder_id, winner.instance_id());
+
+    assert!(winner.release_lock("job").await.unwrap());
+    assert!(loser.try_acquire_lock("job", ttl).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_expired_lease_is_taken_over() {
+    let (a, b, _dir) = two_instances().await;
+    let ttl = Duration::from_millis(200);
+
+    assert!(a.try_acquire_lock("job", ttl).await.unwrap());
+    assert!(!b.try_acquire_lock("job", ttl).await.unwrap());
+
+    // a "崩溃"：不续约也不释放
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(a.active_locks().await.unwrap().is_empty());
+    assert!(b.try_acquire_lock("job", ttl).await.unwrap());
+    // 旧持有者不能再续约
+    assert!(!a.renew_lock("job", ttl).await.unwrap());
+    assert_eq!(a.active_locks().await.unwrap()[0].holder_id, b.instance_id());
+}
+
+#[tokio::test]
+async fn test_renewal_prevents_takeover() {
+    let (a, b, _dir) = two_instances().await;
+    let ttl = Duration::from_millis(300);
+
+    assert!(a.try_acquire_lock("job", ttl).await.unwrap());
+    for _ in 0..4 {
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(a.renew_lock("job", ttl).await.unwrap());
+        assert!(!b.try_acquire_lock("job", ttl).await.unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_scheduler_runs_on_one_instance_and_fails_over() {
+    let (a, b, _dir) = two_instances().await;
+    let interval = Duration::from_millis(50);
+    let grace = Duration::from_millis(150);
+
+    let spawn = |storage: Arc<WalletStorage>, counter: Arc<AtomicUsize>| {
+        let lease = LeaderLease::for_scheduler(storage, "counter", interval, grace);
+        spawn_periodic(interval, false, Some(lease), move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+    };
+    let runs_a = Arc::new(AtomicUsize::new(0));
+    let runs_b = Arc::new(AtomicUsize::new(0));
+    let handle_a = spawn(a.clone(), runs_a.clone());
+    let handle_b = spawn(b.clone(), runs_b.clone());
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let (first_a, first_b) = (runs_a.load(Ordering::SeqCst), runs_b.load(Ordering::SeqCst));
+    assert!(first_a + first_b >= 3, "scheduler should have ticked");
+    assert!(first_a == 0 || first_b == 0, "both instances ran the job (a={}, b={})", first_a, first_b);
+
+    // 停掉持有者（不释放租约），另一个实例在租约过期后接管
+    let (survivor_runs, before) = if first_a > 0 {
+        handle_a.abort();
+        (runs_b.clone(), first_b)
+    } else {
+        handle_b.abort();
+        (runs_a.clone(), first_a)
+    };
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    assert!(survivor_runs.load(Ordering::SeqCst) > before, "survivor never took over");
+
+    handle_a.abort();
+    handle_b.abort();
+}
+
+#[tokio::test]
+async fn test_nonce_reservation_with_leases_stays_unique() {
+    let (a, b, _dir) = two_instances().await;
+    let ttl = Duration::from_millis(500);
+    let a = Arc::new((*a).clone().with_nonce_leases(ttl));
+    let b = Arc::new((*b).clone().with_nonce_leases(ttl));
+
+    let mut tasks = Vec::new();
+    for i in 0..10 {
+        let storage = if i % 2 == 0 { a.clone() } else { b.clone() };
+        tasks.push(tokio::spawn(async move {
+            storage.reserve_next_nonce("eth", "0xAbC0000000000000000000000000000000000001", 7).await
+        }));
+    }
+    let mut nonces = Vec::new();
+    for task in tasks {
+        nonces.push(task.await.unwrap().unwrap());
+    }
+    nonces.sort_unstable();
+    assert_eq!(nonces, (7..17).collect::<Vec<u64>>());
+    // 预留完成后 nonce 租约已释放
+    assert!(a.active_locks().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_nonce_ranges_with_leases_do_not_interleave() {
+    let (a, b, _dir) = two_instances().await;
+    let ttl = Duration::from_millis(500);
+    let a = Arc::new((*a).clone().with_nonce_leases(ttl));
+    let b = Arc::new((*b).clone().with_nonce_leases(ttl));
+    let address = "0xabc0000000000000000000000000000000000003";
+
+    let mut tasks = Vec::new();
+    for i in 0..6 {
+        let storage = if i % 2 == 0 { a.clone() } else { b.clone() };
+        tasks.push(tokio::spawn(async move { storage.reserve_nonce_range("eth", address, 5, 3).await }));
+    }
+    let mut starts = Vec::new();
+    for task in tasks {
+        starts.push(task.await.unwrap().unwrap());
+    }
+    starts.sort_unstable();
+    // 区间从链上 pending 数开始，互不重叠
+    assert_eq!(starts, [5, 8, 11, 14, 17, 20]);
+
+    // 只有最后一个区间的未用尾部能归还
+    assert!(!a.release_nonce_range("eth", address, 6, 8).await.unwrap());
+    assert!(b.release_nonce_range("eth", address, 21, 23).await.unwrap());
+    assert_eq!(a.reserve_next_nonce("eth", address, 0).await.unwrap(), 21);
+    // 存储落后于链上时从 floor 开始
+    assert_eq!(b.reserve_nonce_range("eth", address, 40, 2).await.unwrap(), 40);
+}
+
+#[tokio::test]
+async fn test_shutdown_aborts_nonce_lease_wait() {
+    let (a, b, _dir) = two_instances().await;
+    let ttl = Duration::from_secs(30);
+    let b = Arc::new((*b).clone().with_nonce_leases(ttl));
+    // 另一实例持有租约，等待本应持续到 2 × ttl
+    let lease = "nonce:eth:0xabc0000000000000000000000000000000000002";
+    assert!(a.try_acquire_lock(lease, ttl).await.unwrap());
+
+    let waiter = {
+        let b = b.clone();
+        tokio::spawn(async move {
+            b.reserve_next_nonce("eth", "0xAbC0000000000000000000000000000000000002", 0).await
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    b.begin_shutdown();
+
+    let err = tokio::time::timeout(Duration::from_secs(2), waiter).await.expect("wait not aborted").unwrap().unwrap_err();
+    assert!(err.to_string().contains("aborted by shutdown"), "{}", err);
+}
@@ -0,0 +1,184 @@
+//! 重复发送检测：窗口内与未确认transaction完全相同的转账被拒绝
+
+use async_trait::async_trait;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionRequest, H256, U256};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::core::config::SecurityConfig;
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::{BroadcastChain, IntentError, SendFingerprint, SigningIntentLog};
+use defi_hot_wallet::monitoring::WalletMetrics;
+use defi_hot_wallet::storage::{TransactionRecord, WalletStorage};
+
+const WALLET: &str = "hot";
+const NETWORK: &str = "eth";
+const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+
+#[derive(Default)]
+struct MockChain {
+    received: Mutex<Vec<H256>>,
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        tx.set_nonce(self.received.lock().unwrap().len() as u64);
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(1_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        let hash = H256::from(ethers::utils::keccak256(&raw));
+        self.received.lock().unwrap().push(hash);
+        Ok(hash)
+    }
+
+    async fn transaction_known(&self, _network: &str, tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(self.received.lock().unwrap().contains(&tx_hash))
+    }
+}
+
+struct Harness {
+    storage: Arc<WalletStorage>,
+    log: SigningIntentLog,
+    metrics: Arc<WalletMetrics>,
+    signer: LocalWallet,
+}
+
+impl Harness {
+    async fn new(window_secs: u64) -> Self {
+        // 落库wallet写审计记录，其 MAC 需要 WALLET_ENC_KEY
+        std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+        let storage = Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap());
+        storage.store_wallet(WALLET, b"blob", false).await.unwrap();
+        let metrics = Arc::new(WalletMetrics::new().unwrap());
+        let log = SigningIntentLog::new(storage.clone(), Arc::new(MockChain::default()))
+            .with_duplicate_window(window_secs)
+            .with_metrics(metrics.clone());
+        Self { storage, log, metrics, signer: KEY.parse().unwrap() }
+    }
+
+    /// 与 handler 相同：先检查再发送
+    async fn send(&self, to: &str, amount: &str, allow_duplicate: bool) -> Result<String, IntentError> {
+        let to: Address = to.parse().unwrap();
+        let amount = ethers::utils::parse_ether(amount).unwrap();
+        self.log.check_duplicate(&fingerprint(to, None, amount), allow_duplicate).await?;
+        let tx: TypedTransaction = TransactionRequest::new().to(to).value(amount).into();
+        self.log.send(WALLET, NETWORK, &self.signer, tx).await
+    }
+
+    /// transactions 按 wallets.id 记录
+    async fn recorded(&self) -> Vec<TransactionRecord> {
+        let wallet_id = self.storage.wallet_id(WALLET).await.unwrap().unwrap();
+        self.storage.get_wallet_transactions(&wallet_id).await.unwrap()
+    }
+}
+
+fn fingerprint(to: Address, token: Option<Address>, amount: U256) -> SendFingerprint<'static> {
+    SendFingerprint { wallet_name: WALLET, network: NETWORK, to, token, amount }
+}
+
+#[tokio::test]
+async fn test_back_to_back_identical_send_is_rejected() {
+    let h = Harness::new(120).await;
+    let first = h.send(RECIPIENT, "1.0", false).await.unwrap();
+
+    // 小写address与多余的 0 不能绕过检查
+    let second = h.send(&RECIPIENT.to_lowercase(), "1.000", false).await;
+    match second {
+        Err(e @ IntentError::DuplicateSuspected { .. }) => {
+            assert_eq!(e.code(), "DUPLICATE_TRANSACTION_SUSPECTED");
+            let IntentError::DuplicateSuspected { tx_hash, age_secs } = e else { unreachable!() };
+            assert_eq!(tx_hash.as_deref(), Some(first.as_str()));
+            assert!(age_secs <= 2);
+        }
+        other => panic!("expected duplicate rejection, got {:?}", other),
+    }
+    assert_eq!(h.recorded().await.len(), 1);
+    assert_eq!(h.metrics.duplicate_sends_rejected.get(), 1.0);
+
+    // 金额不同则不是重复
+    h.send(RECIPIENT, "1.5", false).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_in_flight_intent_counts_as_duplicate() {
+    let h = Harness::new(120).await;
+    let to: Address = RECIPIENT.parse().unwrap();
+    let mut tx: TypedTransaction = TransactionRequest::new().to(to).value(U256::exp10(17)).into();
+    tx.set_from(h.signer.address());
+    tx.set_nonce(0u64);
+    tx.set_chain_id(1u64);
+    // 已写入意图但尚未sign
+    h.log.begin(WALLET, NETWORK, &tx).await.unwrap();
+
+    let refused = h.log.check_duplicate(&fingerprint(to, None, U256::exp10(17)), false).await;
+    assert!(matches!(refused, Err(IntentError::DuplicateSuspected { tx_hash: None, .. })));
+}
+
+#[tokio::test]
+async fn test_allow_duplicate_overrides() {
+    let h = Harness::new(120).await;
+    let first = h.send(RECIPIENT, "0.5", false).await.unwrap();
+    let second = h.send(RECIPIENT, "0.5", true).await.unwrap();
+    assert_ne!(first, second);
+    assert_eq!(h.recorded().await.len(), 2);
+    assert_eq!(h.metrics.duplicate_sends_overridden.get(), 1.0);
+    assert_eq!(h.metrics.duplicate_sends_rejected.get(), 0.0);
+}
+
+#[tokio::test]
+async fn test_window_expiry_and_confirmation_clear_duplicate() {
+    let h = Harness::new(1).await;
+    h.send(RECIPIENT, "0.25", false).await.unwrap();
+    assert!(matches!(
+        h.send(RECIPIENT, "0.25", false).await,
+        Err(IntentError::DuplicateSuspected { .. })
+    ));
+
+    tokio::time::sleep(std::time::Duration::from_millis(2100)).await;
+    h.send(RECIPIENT, "0.25", false).await.unwrap();
+
+    // 已确认的transaction不再算作未决
+    let h = Harness::new(120).await;
+    let tx_hash = h.send(RECIPIENT, "0.25", false).await.unwrap();
+    let recorded = h.recorded().await;
+    assert_eq!(recorded[0].tx_hash, tx_hash);
+    h.storage
+        .update_transaction_status(&recorded[0].id, "confirmed", Some(chrono::Utc::now()))
+        .await
+        .unwrap();
+    h.send(RECIPIENT, "0.25", false).await.unwrap();
+    assert_eq!(h.metrics.duplicate_sends_rejected.get(), 0.0);
+}
+
+#[tokio::test]
+async fn test_same_amount_different_token_passes() {
+    let h = Harness::new(120).await;
+    h.send(RECIPIENT, "2", false).await.unwrap();
+
+    let to: Address = RECIPIENT.parse().unwrap();
+    let usdc: Address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".parse().unwrap();
+    let amount = ethers::utils::parse_ether("2").unwrap();
+    h.log.check_duplicate(&fingerprint(to, Some(usdc), amount), false).await.unwrap();
+    // 同一笔原生转账仍被拒绝
+    assert!(h.log.check_duplicate(&fingerprint(to, None, amount), false).await.is_err());
+}
+
+#[tokio::test]
+async fn test_window_from_security_section() {
+    // hot_wallet 启动时整个 [security] 段按 SecurityConfig 反序列化；0 关闭检查
+    let doc: toml::Value = toml::from_str("[security]\nduplicate_send_window_secs = 0\n").unwrap();
+    let security: SecurityConfig = doc["security"].clone().try_into().unwrap();
+    assert_eq!(SecurityConfig::default().duplicate_send_window_secs, 120);
+
+    let h = Harness::new(security.duplicate_send_window_secs).await;
+    h.send(RECIPIENT, "0.75", false).await.unwrap();
+    h.send(RECIPIENT, "0.75", false).await.unwrap();
+    assert_eq!(h.metrics.duplicate_sends_rejected.get(), 0.0);
+}
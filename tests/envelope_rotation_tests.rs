@@ -0,0 +1,209 @@
+//! envelope KEK 轮换：dry run 的逐wallet探测、回滚保证、与实际运行的报告一致性及模式迁移的阻塞项
+
+use base64::Engine;
+use sqlx::Row;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+use defi_hot_wallet::core::wallet::create::create_wallet;
+use defi_hot_wallet::crypto::quantum::QuantumSafeEncryption;
+use defi_hot_wallet::ops::envelope_rotation::{EnvelopeMode, EnvelopeRotation, ProbeStatus, RotationReport};
+use defi_hot_wallet::security::approval_key::ApprovalKey;
+use defi_hot_wallet::storage::journal_events::KEY_ROTATED;
+use defi_hot_wallet::storage::{
+    ApprovalPayload, ApprovalRecord, WalletStorage, WalletStorageTrait, APPROVAL_PENDING,
+};
+
+const OLD: [u8; 32] = [7; 32];
+const NEW: [u8; 32] = [9; 32];
+const OTHER: [u8; 32] = [5; 32];
+
+fn kek(bytes: [u8; 32]) -> Zeroizing<[u8; 32]> {
+    Zeroizing::new(bytes)
+}
+
+/// 只为占一行 pending 审批，不会被打开
+fn placeholder_key() -> ApprovalKey {
+    ApprovalKey { salt: vec![0; 32], nonce: vec![0; 12], ciphertext: vec![0; 48] }
+}
+
+struct Db {
+    _dir: tempfile::TempDir,
+    url: String,
+    storage: Arc<WalletStorage>,
+}
+
+async fn open() -> Db {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display());
+    let storage = Arc::new(WalletStorage::new_with_url(&url).await.unwrap());
+    Db { _dir: dir, url, storage }
+}
+
+impl Db {
+    /// 用 `kek` 作为 WALLET_ENC_KEY 创建wallet
+    async fn seed(&self, name: &str, kek: [u8; 32]) {
+        std::env::set_var("WALLET_ENC_KEY", base64::engine::general_purpose::STANDARD.encode(kek));
+        let storage: Arc<dyn WalletStorageTrait + Send + Sync> = self.storage.clone();
+        create_wallet(&storage, &QuantumSafeEncryption::new().unwrap(), name, false).await.unwrap();
+    }
+
+    /// wallets 表与 journal 的原始内容，用于断言没有任何行被改动
+    async fn snapshot(&self) -> (Vec<(String, Vec<u8>, bool, String)>, i64) {
+        let pool = sqlx::SqlitePool::connect(&self.url).await.unwrap();
+        let rows = sqlx::query(
+            "SELECT name, encrypted_data, quantum_safe, CAST(updated_at AS TEXT) AS updated_at \
+             FROM wallets ORDER BY name",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| (r.get("name"), r.get("encrypted_data"), r.get("quantum_safe"), r.get("updated_at")))
+        .collect();
+        let journal = sqlx::query_scalar("SELECT COUNT(*) FROM events_journal").fetch_one(&pool).await.unwrap();
+        pool.close().await;
+        (rows, journal)
+    }
+
+    async fn rotate(&self, old: [u8; 32], new: [u8; 32], dry_run: bool) -> RotationReport {
+        EnvelopeRotation::new(kek(old), kek(new)).run(&self.storage, dry_run).await.unwrap()
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_dry_run_probes_every_wallet_without_writing() {
+    let db = open().await;
+    db.seed("alpha", OLD).await;
+    db.seed("beta", OLD).await;
+    // 用另一个 KEK 密封，旧 KEK 的解密探测必然失败
+    db.seed("stray", OTHER).await;
+    let before = db.snapshot().await;
+
+    let report = db.rotate(OLD, NEW, true).await;
+    assert!(report.dry_run);
+    assert!(!report.applied);
+    assert_eq!(report.totals.wallets, 3);
+    assert_eq!(report.totals.succeeded, 2);
+    assert_eq!(report.totals.failed, 1);
+    assert_eq!(report.totals.decrypt_failed, 1);
+    assert_eq!(report.totals.mode_changes, 0);
+    let names: Vec<_> = report.wallets.iter().map(|w| w.wallet.as_str()).collect();
+    assert_eq!(names, ["alpha", "beta", "stray"]);
+    for w in &report.wallets[..2] {
+        assert_eq!((w.decrypt_probe, w.encrypt_probe), (ProbeStatus::Passed, ProbeStatus::Passed), "{:?}", w);
+        assert!(w.succeeded());
+    }
+    let stray = &report.wallets[2];
+    assert_eq!((stray.decrypt_probe, stray.encrypt_probe), (ProbeStatus::Failed, ProbeStatus::Skipped));
+    assert!(stray.error.as_deref().unwrap().contains("not sealed under this KEK"));
+    assert_eq!(db.snapshot().await, before);
+
+    // 实际运行同样不写：只要有一个wallet失败就整体回滚
+    let real = db.rotate(OLD, NEW, false).await;
+    assert!(!real.applied);
+    assert_eq!(real.totals, report.totals);
+    assert_eq!(db.snapshot().await, before);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_dry_run_report_matches_the_real_run() {
+    let db = open().await;
+    db.seed("alpha", OLD).await;
+    db.seed("beta", OLD).await;
+    let before = db.snapshot().await;
+
+    let dry = db.rotate(OLD, NEW, true).await;
+    assert_eq!(db.snapshot().await, before);
+    let real = db.rotate(OLD, NEW, false).await;
+    assert!(real.applied);
+    assert_eq!(real.totals.succeeded, 2);
+    assert_eq!(RotationReport { dry_run: false, applied: true, ..dry }, real);
+
+    let (rows, journal) = db.snapshot().await;
+    assert_eq!(journal, before.1 + 1);
+    for (row, old) in rows.iter().zip(&before.0) {
+        assert_ne!(row.1, old.1, "{} was not resealed", row.0);
+    }
+    let event = db.storage.journal_events(0, 100).await.unwrap().pop().unwrap();
+    assert_eq!(event.event_type, KEY_ROTATED);
+    assert_eq!(event.entity_id, real.kek_id);
+    assert_eq!(event.payload["wallets"], 2);
+
+    // 旧 KEK 已打不开，新 KEK 可继续轮换
+    assert_eq!(db.rotate(OLD, NEW, true).await.totals.decrypt_failed, 2);
+    assert_eq!(db.rotate(NEW, OTHER, true).await.totals.succeeded, 2);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_mode_migration_is_blocked_by_pending_approvals() {
+    std::env::set_var("WALLET_TEST_CONSTRUCTOR", "1");
+    let db = open().await;
+    db.seed("alpha", OLD).await;
+    db.seed("beta", OLD).await;
+    db.storage
+        .create_approval(
+            &ApprovalRecord {
+                id: "approval-1".to_string(),
+                wallet_name: "alpha".to_string(),
+                network: "eth".to_string(),
+                payload: ApprovalPayload {
+                    from: "0x000000000000000000000000000000000000a11c".to_string(),
+                    to: "0x000000000000000000000000000000000000b0b0".to_string(),
+                    value: "1000000000000000000".to_string(),
+                    token: None,
+                    allow_duplicate: false,
+                },
+                amount: "1".to_string(),
+                requested_by: "admin".to_string(),
+                token_id: None,
+                status: APPROVAL_PENDING.to_string(),
+                decided_by: None,
+                reason: None,
+                tx_hash: None,
+                created_at: 1_700_000_000,
+                expires_at: 1_700_003_600,
+                decided_at: None,
+            },
+            &placeholder_key(),
+        )
+        .await
+        .unwrap();
+    let before = db.snapshot().await;
+
+    let migrate = || EnvelopeRotation::new(kek(OLD), kek(NEW)).with_target(EnvelopeMode::Quantum);
+    let report = migrate().run(&db.storage, true).await.unwrap();
+    assert_eq!(report.target_mode, Some(EnvelopeMode::Quantum));
+    assert_eq!(report.totals.mode_changes, 2);
+    assert_eq!(report.totals.blocked, 1);
+    let alpha = &report.wallets[0];
+    assert_eq!(alpha.blockers, ["1 pending approvals"]);
+    assert_eq!((alpha.decrypt_probe, alpha.encrypt_probe), (ProbeStatus::Passed, ProbeStatus::Passed));
+    assert!(!alpha.succeeded());
+    assert!(report.wallets[1].succeeded());
+    assert!(!migrate().run(&db.storage, false).await.unwrap().applied);
+    assert_eq!(db.snapshot().await, before);
+
+    // 无阻塞项时迁移到 quantum 再迁回 classical
+    let clean = open().await;
+    clean.seed("gamma", OLD).await;
+    let report = EnvelopeRotation::new(kek(OLD), kek(NEW))
+        .with_target(EnvelopeMode::Quantum)
+        .run(&clean.storage, false)
+        .await
+        .unwrap();
+    assert!(report.applied);
+    assert!(clean.snapshot().await.0[0].2);
+    let back = EnvelopeRotation::new(kek(NEW), kek(OLD))
+        .with_target(EnvelopeMode::Classical)
+        .with_kek_id("kek-restored")
+        .run(&clean.storage, false)
+        .await
+        .unwrap();
+    assert!(back.applied);
+    assert_eq!(back.wallets[0].from_mode, EnvelopeMode::Quantum);
+    assert!(!clean.snapshot().await.0[0].2);
+}
@@ -0,0 +1,227 @@
+//! ERC-20 授权：`GET /api/wallets/:name/allowance` 经 eth_call 查询额度，
+//! `POST /api/wallets/:name/approve` 模拟通过后sign广播；零address spender 与非 u256 数额返回 400
+
+mod util;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::providers::{JsonRpcError, MockProvider, MockResponse, Provider};
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionReceipt, H256, U256};
+use ethers::utils::rlp::Rlp;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+use defi_hot_wallet::blockchain::gas_oracle::ProviderGasOracle;
+use defi_hot_wallet::core::domain::Tx;
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+
+const API_KEY: &str = "erc20-allowance-admin-key-0123456789";
+const SESSION: &str = "erc20-allowance-session-token";
+const WALLET: &str = "approver";
+const PASSWORD: &str = "Appr0ve!Vault#2024";
+const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+const ROUTER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+
+/// 节点：只记录广播的transaction
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    sent: Mutex<Vec<TypedTransaction>>,
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        tx.set_gas(60_000u64);
+        tx.set_gas_price(3_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        let (tx, _) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        self.sent.lock().unwrap().push(tx);
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        _tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        Ok(None)
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    chain: Arc<MockChain>,
+    /// 链客户端（allowance 查询）的应答，后进先出
+    client: MockProvider,
+    /// approve 模拟（gas oracle）的应答
+    oracle: MockProvider,
+    owner: Address,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = Arc::new(MockChain::default());
+    let (client_provider, client) = Provider::<MockProvider>::mocked();
+    let (oracle_provider, oracle) = Provider::<MockProvider>::mocked();
+    let server = util::test_server(dir.path(), util::file_config(dir.path()), Some(API_KEY))
+        .await
+        .with_broadcast_chain(chain.clone())
+        .with_gas_oracle(Arc::new(ProviderGasOracle::<MockProvider>::new().with_provider("eth", oracle_provider)))
+        .with_chain_clients(
+            ClientRegistry::new()
+                .with_client("eth", Arc::new(EthereumClient::new_with_provider_and_chain(client_provider, "eth", 1))),
+        );
+
+    let user_id = util::sign_in(&server, "approver@example.com", SESSION).await;
+    util::create_wallet(&server, WALLET, PASSWORD).await;
+    let owner = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+    server.user_db.link_wallet(&user_id, WALLET, &format!("{:#x}", owner), None).await.unwrap();
+
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, chain, client, oracle, owner, _dir: dir }
+}
+
+impl Harness {
+    async fn allowance(&self, network: &str, spender: &str) -> axum_test::TestResponse {
+        self.app
+            .get(&format!("/api/wallets/{}/allowance", WALLET))
+            .add_query_param("network", network)
+            .add_query_param("token", USDC)
+            .add_query_param("spender", spender)
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .await
+    }
+
+    async fn approve(&self, spender: &str, amount: &str) -> axum_test::TestResponse {
+        self.approve_with(spender, amount, false).await
+    }
+
+    async fn approve_with(&self, spender: &str, amount: &str, allow_unlimited: bool) -> axum_test::TestResponse {
+        self.app
+            .post(&format!("/api/wallets/{}/approve", WALLET))
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .json(&json!({
+                "network": "eth",
+                "token": USDC,
+                "spender": spender,
+                "amount": amount,
+                "password": PASSWORD,
+                "allow_unlimited": allow_unlimited,
+            }))
+            .await
+    }
+}
+
+fn bool_word(value: bool) -> Bytes {
+    let mut word = [0u8; 32];
+    word[31] = value as u8;
+    Bytes::from(word.to_vec())
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_allowance_is_read_for_the_wallet_address() {
+    let h = build().await;
+    h.client.push::<String, _>(format!("0x{:064x}", 1_500_000u64)).unwrap();
+
+    let res = h.allowance("eth", ROUTER).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["allowance"], "1500000");
+    assert_eq!(body["owner"], format!("{:#x}", h.owner));
+    assert_eq!(body["spender"], ROUTER);
+    assert_eq!(body["token"], USDC);
+
+    // 比特币没有 ERC-20
+    let res = h.allowance("btc", ROUTER).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "UNSUPPORTED_NETWORK");
+
+    // address上没有合约：eth_call 返回空数据
+    h.client.push::<String, _>("0x".to_string()).unwrap();
+    let res = h.allowance("eth", ROUTER).await;
+    assert_eq!(res.status_code(), 422);
+    assert_eq!(res.json::<Value>()["error"]["code"], "TOKEN_BAD_RETURN_DATA");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_approve_signs_and_broadcasts_the_approval() {
+    let h = build().await;
+    h.oracle.push::<Bytes, _>(bool_word(true)).unwrap();
+
+    let res = h.approve(ROUTER, "2500000").await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["amount"], "2500000");
+    assert!(body["tx_hash"].as_str().unwrap().starts_with("0x"));
+
+    let sent = h.chain.sent.lock().unwrap().clone();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(*sent[0].to_addr().unwrap(), USDC.parse::<Address>().unwrap());
+    assert_eq!(sent[0].data().unwrap().to_vec(), Tx::encode_erc20_approve_calldata(ROUTER, "2500000").unwrap());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_approve_rejects_zero_spender_bad_amounts_and_reverts() {
+    let h = build().await;
+
+    let res = h.approve("0x0000000000000000000000000000000000000000", "1").await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_ADDRESS");
+
+    let too_big = "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+    for amount in ["1.5", "-1", "0x10", "", too_big] {
+        let res = h.approve(ROUTER, amount).await;
+        res.assert_status_bad_request();
+        assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_AMOUNT", "{:?}", amount);
+    }
+
+    // 模拟 revert：不上链
+    h.oracle.push_response(MockResponse::Error(JsonRpcError {
+        code: 3,
+        message: "execution reverted".to_string(),
+        data: None,
+    }));
+    let res = h.approve(ROUTER, "1").await;
+    assert_eq!(res.status_code(), 422);
+    assert_eq!(res.json::<Value>()["error"]["code"], "TOKEN_CALL_REVERTED");
+    assert!(h.chain.sent.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_unlimited_approval_must_be_confirmed() {
+    let h = build().await;
+    let max = U256::MAX.to_string();
+
+    let res = h.approve(ROUTER, &max).await;
+    assert_eq!(res.status_code(), 422);
+    assert_eq!(res.json::<Value>()["error"]["code"], "UNLIMITED_APPROVAL_UNCONFIRMED");
+    assert!(h.chain.sent.lock().unwrap().is_empty());
+
+    h.oracle.push::<Bytes, _>(bool_word(true)).unwrap();
+    let res = h.approve_with(ROUTER, &max, true).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["amount"], max);
+    assert_eq!(h.chain.sent.lock().unwrap().len(), 1);
+}
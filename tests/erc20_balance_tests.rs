@@ -0,0 +1,113 @@
+//! `GET /api/wallets/:name/balance?token_address=`：经 MockProvider 的 eth_call 查询 ERC-20
+//! balance，token address非法返回 400，合约 revert 返回 422 而不是 500
+
+mod util;
+
+use axum_test::TestServer;
+use ethers::providers::{JsonRpcError, MockProvider, MockResponse, Provider};
+use serde_json::Value;
+use std::sync::Arc;
+
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+
+const API_KEY: &str = "erc20-balance-admin-key";
+const SESSION: &str = "erc20-balance-session-token";
+const WALLET: &str = "token_wallet";
+const ADDRESS: &str = "0x1234567890123456789012345678901234567890";
+const TOKEN: &str = "0xdac17f958d2ee523a2206206994597c13d831ec7";
+
+struct Harness {
+    app: TestServer,
+    mock: MockProvider,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    let client = EthereumClient::new_with_provider_and_chain(provider, "eth", 1);
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY))
+        .await
+        .with_chain_clients(ClientRegistry::new().with_client("eth", Arc::new(client)));
+
+    let user_id = util::sign_in(&server, "erc20@example.com", SESSION).await;
+    server.user_db.link_wallet(&user_id, WALLET, ADDRESS, None).await.unwrap();
+
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, mock, _dir: dir }
+}
+
+impl Harness {
+    async fn token_balance(&self, network: &str, token: &str) -> axum_test::TestResponse {
+        self.app
+            .get(&format!("/api/wallets/{}/balance", WALLET))
+            .add_query_param("network", network)
+            .add_query_param("token_address", token)
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .await
+    }
+}
+
+fn uint_word(value: u64) -> String {
+    format!("0x{:064x}", value)
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_token_balance_reports_raw_amount_and_decimals() {
+    let h = build().await;
+    // MockProvider 后进先出：先 balanceOf，再 decimals
+    h.mock.push::<String, _>(uint_word(6)).unwrap();
+    h.mock.push::<String, _>(uint_word(1_500_000)).unwrap();
+
+    let res = h.token_balance("eth", TOKEN).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["balance"], "1.500000");
+    assert_eq!(body["raw_balance"], "1500000");
+    assert_eq!(body["decimals"], 6);
+    assert_eq!(body["token_address"], TOKEN);
+    assert_eq!(body["network"], "eth");
+    assert!(body.get("fiat_value").is_none());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_invalid_token_address_is_rejected() {
+    let h = build().await;
+    for token in ["0x1234", "0xZZc17f958d2ee523a2206206994597c13d831ec7"] {
+        let res = h.token_balance("eth", token).await;
+        res.assert_status_bad_request();
+        let body: Value = res.json();
+        assert_eq!(body["error"]["code"], "INVALID_ADDRESS", "{}", token);
+    }
+
+    // 比特币没有 ERC-20
+    let res = h.token_balance("btc", TOKEN).await;
+    res.assert_status_bad_request();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "UNSUPPORTED_NETWORK");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_reverting_token_is_not_a_server_error() {
+    let h = build().await;
+    h.mock.push_response(MockResponse::Error(JsonRpcError {
+        code: 3,
+        message: "execution reverted".to_string(),
+        data: None,
+    }));
+    let res = h.token_balance("eth", TOKEN).await;
+    assert_eq!(res.status_code(), 422);
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "TOKEN_CALL_REVERTED");
+
+    // address上没有合约：eth_call 返回空数据
+    h.mock.push::<String, _>("0x".to_string()).unwrap();
+    let res = h.token_balance("eth", TOKEN).await;
+    assert_eq!(res.status_code(), 422);
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "TOKEN_BAD_RETURN_DATA");
+}
@@ -0,0 +1,381 @@
+//! 非标准 ERC-20：不返回 bool 的 token、黑名单 revert 的说明、fee-on-transfer 按实际到账记账、
+//! 成功却未到账的静默失败，以及 max_approval_disallowed 强制按数额授权
+
+mod util;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::abi::{self, Token};
+use ethers::providers::{JsonRpcError, MockProvider, MockResponse, Provider};
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, Log, TransactionReceipt, H256, U256, U64};
+use ethers::utils::{rlp::Rlp, to_checksum};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::anomaly_detection::{AnomalyDetector, DetectionMode};
+use defi_hot_wallet::blockchain::erc20::{transfer_calldata, TokenBehavior};
+use defi_hot_wallet::blockchain::gas_oracle::ProviderGasOracle;
+use defi_hot_wallet::blockchain::history::transfer_topic;
+use defi_hot_wallet::core::abi::selector_from_signature;
+use defi_hot_wallet::core::config::{FeeTrackingConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::ops::fee_tracking::ConfirmationPoller;
+use defi_hot_wallet::storage::{WalletStorage, DELIVERY_PENDING, DELIVERY_SHORT, DELIVERY_UNDELIVERED};
+use defi_hot_wallet::token_registry::TokenRegistry;
+
+const API_KEY: &str = "erc20-tokens-admin-key-0123456789";
+const TOKEN: &str = "erc20-tokens-user-token";
+const EMAIL: &str = "tokens@example.com";
+const WALLET: &str = "token-sender";
+const PASSWORD: &str = "T0kenS3nder!Vault#2024";
+const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+const USDT: &str = "0xdac17f958d2ee523a2206206994597c13d831ec7";
+/// 每笔转账扣 2% 的 token
+const TAXED: &str = "0x00000000000000000000000000000000000f0e70";
+const ROUTER: &str = "0x1111111254eeb25477b68fb85ed929f73a960582";
+const ALICE: &str = "0x000000000000000000000000000000000000a11c";
+/// fee-on-transfer token 收取转账费的address
+const FEE_SINK: &str = "0x000000000000000000000000000000000000fee5";
+
+fn address(text: &str) -> Address {
+    text.parse().unwrap()
+}
+
+/// 节点：记录广播的transaction；回执中的 Transfer 日志按 `deliveries` 生成（为空即成功但没有转账）
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    sent: Mutex<Vec<(H256, TypedTransaction)>>,
+    deliveries: Mutex<Vec<(Address, u64)>>,
+}
+
+impl MockChain {
+    fn sent(&self) -> Vec<TypedTransaction> {
+        self.sent.lock().unwrap().iter().map(|(_, tx)| tx.clone()).collect()
+    }
+
+    fn deliver(&self, deliveries: &[(&str, u64)]) {
+        *self.deliveries.lock().unwrap() = deliveries.iter().map(|(to, amount)| (address(to), *amount)).collect();
+    }
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        tx.set_gas(60_000u64);
+        tx.set_gas_price(3_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        let (tx, _) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        let hash = H256::from(ethers::utils::keccak256(&raw));
+        self.sent.lock().unwrap().push((hash, tx));
+        Ok(hash)
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        let sent = self.sent.lock().unwrap();
+        let Some((_, tx)) = sent.iter().find(|(hash, _)| *hash == tx_hash) else { return Ok(None) };
+        let (from, token) = (*tx.from().unwrap(), *tx.to_addr().unwrap());
+        let logs = self
+            .deliveries
+            .lock()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .map(|(i, (to, amount))| {
+                let mut data = [0u8; 32];
+                U256::from(*amount).to_big_endian(&mut data);
+                Log {
+                    address: token,
+                    topics: vec![transfer_topic(), H256::from(from), H256::from(*to)],
+                    data: Bytes::from(data.to_vec()),
+                    block_number: Some(U64::from(100)),
+                    transaction_hash: Some(tx_hash),
+                    log_index: Some(U256::from(i)),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        Ok(Some(TransactionReceipt {
+            transaction_hash: tx_hash,
+            block_number: Some(U64::from(100)),
+            from,
+            to: Some(token),
+            logs,
+            gas_used: Some(U256::from(52_000u64)),
+            effective_gas_price: Some(U256::from(3_000_000_000u64)),
+            status: Some(U64::from(1)),
+            ..Default::default()
+        }))
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    storage: Arc<WalletStorage>,
+    chain: Arc<MockChain>,
+    /// eth_call 的应答，后进先出
+    node: MockProvider,
+}
+
+fn bool_word(value: bool) -> Bytes {
+    let mut word = [0u8; 32];
+    word[31] = value as u8;
+    Bytes::from(word.to_vec())
+}
+
+fn revert_with(reason: &str) -> MockResponse {
+    let mut data = selector_from_signature("Error(string)").to_vec();
+    data.extend(abi::encode(&[Token::String(reason.to_string())]));
+    MockResponse::Error(JsonRpcError {
+        code: 3,
+        message: "execution reverted".to_string(),
+        data: Some(Value::String(format!("0x{}", hex::encode(data)))),
+    })
+}
+
+/// `TAXED` 在配置中标为 fee-on-transfer 并check到账，`USDT`（校验和大小写）标为不返回 bool
+fn config(dir: &tempfile::TempDir) -> WalletConfig {
+    let mut config = util::file_config(dir.path());
+    let taxed = TokenBehavior { fee_on_transfer: true, verify_delivery: true, ..Default::default() };
+    let usdt = TokenBehavior { no_bool_return: true, ..Default::default() };
+    let tokens = HashMap::from([(TAXED.to_string(), taxed), (to_checksum(&address(USDT), None), usdt)]);
+    config.tokens.behaviors.insert("eth".to_string(), tokens);
+    config
+}
+
+async fn build(dir: &tempfile::TempDir) -> Harness {
+    let chain = Arc::new(MockChain::default());
+    let (provider, node) = Provider::mocked();
+    let mut detector = AnomalyDetector::new();
+    detector.set_mode(DetectionMode::WarnOnly);
+    let server = util::test_server(dir.path(), config(dir), Some(API_KEY))
+        .await
+        .with_broadcast_chain(chain.clone())
+        .with_gas_oracle(Arc::new(ProviderGasOracle::<MockProvider>::new().with_provider("eth", provider)))
+        .with_bundle_detector(detector);
+
+    let user_id = util::sign_in(&server, EMAIL, TOKEN).await;
+    util::create_wallet(&server, WALLET, PASSWORD).await;
+    let sender = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+    server.user_db.link_wallet(&user_id, WALLET, &format!("{:#x}", sender), None).await.unwrap();
+
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, storage, chain, node }
+}
+
+impl Harness {
+    async fn submit(&self, steps: Value) -> Value {
+        let res = self
+            .app
+            .post(&format!("/api/wallets/{}/bundles", WALLET))
+            .add_header("Authorization", format!("Bearer {}", TOKEN))
+            .json(&json!({ "password": PASSWORD, "steps": steps }))
+            .await;
+        res.assert_status_ok();
+        res.json::<Value>()["steps"][0].clone()
+    }
+
+    async fn send_tokens(&self, token: &str, amount: u64) -> Value {
+        let data = transfer_calldata(address(ALICE), U256::from(amount));
+        self.submit(json!([{ "id": "send", "kind": "contract_call", "network": "eth", "to": token, "data": data }]))
+            .await
+    }
+
+    /// 轮询一次回执（与后台 fee_confirmations 任务同一路径）
+    async fn confirm(&self) {
+        let poller = ConfirmationPoller::new(FeeTrackingConfig::default(), self.storage.clone(), self.chain.clone());
+        poller.run_once().await;
+    }
+}
+
+/// 广播出去的 approve 授权额度
+fn approved_amount(tx: &TypedTransaction) -> U256 {
+    let data = tx.data().unwrap();
+    assert_eq!(data[..4], selector_from_signature("approve(address,uint256)"));
+    U256::from_big_endian(&data[36..68])
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_max_approval_disallowed_forces_exact_approvals() {
+    let dir = tempfile::tempdir().unwrap();
+    let h = build(&dir).await;
+    let approve = json!([{ "id": "approve", "kind": "approve", "network": "eth", "token": USDC, "spender": ROUTER,
+                           "amount": "1000", "decimals": 6, "unlimited": true }]);
+
+    let res = h
+        .app
+        .put(&format!("/api/admin/tokens/eth/{}", to_checksum(&address(USDC), None)))
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "max_approval_disallowed": true, "updated_by": "ops@example.com" }))
+        .await;
+    res.assert_status_ok();
+    let record: Value = res.json();
+    assert_eq!(record["token"], USDC);
+    assert_eq!(record["max_approval_disallowed"], true);
+
+    h.node.push::<Bytes, _>(bool_word(true)).unwrap();
+    let step = h.submit(approve.clone()).await;
+    assert_eq!(step["status"], "completed", "{}", step);
+    assert_eq!(approved_amount(&h.chain.sent()[0]), U256::from(1_000_000_000u64));
+
+    // 删除设置后回落到标准行为：按请求无限授权
+    let res = h.app.delete(&format!("/api/admin/tokens/eth/{}", USDC)).add_header("X-API-KEY", API_KEY).await;
+    res.assert_status(axum::http::StatusCode::NO_CONTENT);
+    h.node.push::<Bytes, _>(bool_word(true)).unwrap();
+    let step = h.submit(approve).await;
+    assert_eq!(step["status"], "completed", "{}", step);
+    assert_eq!(approved_amount(&h.chain.sent()[1]), U256::MAX);
+
+    let res = h.app.delete(&format!("/api/admin/tokens/eth/{}", USDC)).add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_not_found();
+    let audits = h.storage.get_audit_logs(Some("system")).await.unwrap();
+    assert!(audits.iter().any(|a| a.action == "token_behavior.updated"));
+    assert!(audits.iter().any(|a| a.action == "token_behavior.cleared"));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_empty_return_data_is_success_only_for_flagged_tokens() {
+    let dir = tempfile::tempdir().unwrap();
+    let h = build(&dir).await;
+
+    // USDT 的 transfer 不返回任何数据
+    h.node.push::<Bytes, _>(Bytes::default()).unwrap();
+    let step = h.send_tokens(USDT, 5_000_000).await;
+    assert_eq!(step["status"], "completed", "{}", step);
+    assert_eq!(h.chain.sent().len(), 1);
+
+    // 同样的应答来自未标记的 token：不上链
+    h.node.push::<Bytes, _>(Bytes::default()).unwrap();
+    let step = h.send_tokens(USDC, 5_000_000).await;
+    assert_eq!(step["status"], "failed");
+    assert_eq!(step["error_code"], "TOKEN_NO_RETURN_DATA");
+
+    // 返回 false 同样拒绝
+    h.node.push::<Bytes, _>(bool_word(false)).unwrap();
+    let step = h.send_tokens(USDC, 5_000_000).await;
+    assert_eq!(step["error_code"], "TOKEN_CALL_RETURNED_FALSE");
+    assert_eq!(h.chain.sent().len(), 1);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_blocklist_revert_is_explained_and_not_broadcast() {
+    let dir = tempfile::tempdir().unwrap();
+    let h = build(&dir).await;
+
+    h.node.push_response(revert_with("Blacklistable: account is blacklisted"));
+    let step = h.send_tokens(USDC, 1_000).await;
+    assert_eq!(step["status"], "failed");
+    assert_eq!(step["error_code"], "TOKEN_CALL_REVERTED");
+    let error = step["error"].as_str().unwrap();
+    assert!(error.contains("blocked address") && error.contains("account is blacklisted"), "{}", error);
+    assert!(h.chain.sent().is_empty());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_fee_on_transfer_shortfall_is_booked_at_the_delivered_amount() {
+    let dir = tempfile::tempdir().unwrap();
+    let h = build(&dir).await;
+
+    h.node.push::<Bytes, _>(bool_word(true)).unwrap();
+    let step = h.send_tokens(TAXED, 100).await;
+    assert_eq!(step["status"], "completed", "{}", step);
+    let tx_hash = step["output"]["tx_hash"].as_str().unwrap().to_string();
+    let delivery = h.storage.token_delivery("eth", &tx_hash).await.unwrap().unwrap();
+    assert_eq!((delivery.status.as_str(), delivery.sent.as_str()), (DELIVERY_PENDING, "100"));
+    assert!(delivery.fee_on_transfer);
+
+    // 98 到账，2 作为转账费转给 FEE_SINK
+    h.chain.deliver(&[(ALICE, 98), (FEE_SINK, 2)]);
+    h.confirm().await;
+
+    let delivery = h.storage.token_delivery("eth", &tx_hash).await.unwrap().unwrap();
+    assert_eq!((delivery.status.as_str(), delivery.delivered.as_deref()), (DELIVERY_SHORT, Some("98")));
+    let mut entries: Vec<_> = h
+        .storage
+        .ledger_entries(WALLET, "eth")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|e| (e.direction, e.token, e.counterparty.unwrap(), e.amount, e.source))
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            ("out".to_string(), TAXED.to_string(), ALICE.to_string(), "98".to_string(), "receipt".to_string()),
+            ("out".to_string(), TAXED.to_string(), FEE_SINK.to_string(), "2".to_string(), "receipt".to_string()),
+        ]
+    );
+    assert_eq!(h.storage.transaction_by_hash(&tx_hash).await.unwrap().unwrap().status, "confirmed");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_delivery_check_catches_a_silent_failure() {
+    let dir = tempfile::tempdir().unwrap();
+    let h = build(&dir).await;
+
+    h.node.push::<Bytes, _>(bool_word(true)).unwrap();
+    let step = h.send_tokens(TAXED, 100).await;
+    let tx_hash = step["output"]["tx_hash"].as_str().unwrap().to_string();
+
+    // transaction成功，但 token 没有记录任何转账
+    h.chain.deliver(&[]);
+    h.confirm().await;
+
+    let delivery = h.storage.token_delivery("eth", &tx_hash).await.unwrap().unwrap();
+    assert_eq!((delivery.status.as_str(), delivery.delivered.as_deref()), (DELIVERY_UNDELIVERED, Some("0")));
+    assert!(h.storage.ledger_entries(WALLET, "eth").await.unwrap().is_empty());
+
+    // 之后的轮询不会改写已结算的结果
+    h.chain.deliver(&[(ALICE, 100)]);
+    h.confirm().await;
+    let again = h.storage.token_delivery("eth", &tx_hash).await.unwrap().unwrap();
+    assert_eq!(again.status, DELIVERY_UNDELIVERED);
+}
+
+#[tokio::test]
+async fn test_stored_behavior_takes_precedence_over_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap());
+    let registry = TokenRegistry::from_config(&config(&dir), storage).unwrap();
+    let usdt = address(USDT);
+
+    assert!(registry.behavior("eth", usdt).await.no_bool_return);
+    assert_eq!(registry.behavior("polygon", usdt).await, TokenBehavior::default());
+
+    let strict = TokenBehavior { max_approval_disallowed: true, ..Default::default() };
+    registry.set("eth", USDT, &strict, "ops").await.unwrap();
+    assert_eq!(registry.behavior("eth", usdt).await, strict);
+    let listed = registry.list().await.unwrap();
+    let entry = listed.iter().find(|e| e.token == USDT).unwrap();
+    assert_eq!((entry.behavior, entry.configured.unwrap().no_bool_return), (strict, true));
+
+    assert!(registry.clear("eth", USDT).await.unwrap());
+    assert!(registry.behavior("eth", usdt).await.no_bool_return);
+    assert!(registry.set("eth", "not-an-address", &strict, "ops").await.is_err());
+}
@@ -0,0 +1,326 @@
+//! ERC-4337：userOpHash 固定向量、SimpleAccount calldata，以及经 mock
+//! bundler / 节点的 `POST /api/wallets/:name/aa/send` 到上链的完整流程
+
+mod util;
+
+use axum_test::TestServer;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, Signature, H256, U256};
+use httpmock::{Method, MockServer};
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::erc4337::{encode_execute, init_code, UserOperation};
+
+const ENTRY_POINT: &str = "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789";
+const FACTORY: &str = "0x9406Cc6185a346906296840746125a0E44976454";
+const SESSION: &str = "erc4337-session";
+const PASSWORD: &str = "Aa!Owner#Passw0rd";
+const ACCOUNT: &str = "0x1306b01bc3e4ad202612d3843387e94737673f53";
+const OP_HASH: &str = "0x481a43fbdd21cc864de7fa3a3c1a40b4ccba632c608181af57ea55a5915512ac";
+const BUNDLE_TX: &str = "0x9a0f6a4a6d3f2bd1c1f0bd2f2a61d0b0e1f4c8e3a9d5b7c6e2f1a0b9c8d7e6f5";
+
+fn fixture_op() -> UserOperation {
+    UserOperation {
+        sender: ACCOUNT.parse().unwrap(),
+        nonce: U256::from(7),
+        init_code: init_code(FACTORY.parse().unwrap(), Address::repeat_byte(0xab), U256::zero()),
+        call_data: Bytes::from(vec![0xb6, 0x1d, 0x27, 0xf6]),
+        call_gas_limit: U256::from(35_000),
+        verification_gas_limit: U256::from(390_000),
+        pre_verification_gas: U256::from(46_856),
+        max_fee_per_gas: U256::from(2_000_000_000u64),
+        max_priority_fee_per_gas: U256::from(1_500_000_000u64),
+        paymaster_and_data: Bytes::new(),
+        signature: Bytes::new(),
+    }
+}
+
+#[test]
+fn test_user_op_hash_fixture() {
+    let op = fixture_op();
+    assert_eq!(
+        hex::encode(&op.init_code),
+        "9406cc6185a346906296840746125a0e449764545fbfb9cf000000000000000000000000abababababababababababababababababababab0000000000000000000000000000000000000000000000000000000000000000"
+    );
+    let entry_point: Address = ENTRY_POINT.parse().unwrap();
+    assert_eq!(format!("{:?}", op.hash(entry_point, 1)), OP_HASH);
+    assert_eq!(
+        format!("{:?}", op.hash(entry_point, 137)),
+        "0xfd6c4a7bf35854334aafc1db91d493955eef0138076a47da427968ab0b3cefd2"
+    );
+
+    // 签名不参与哈希
+    let mut signed = op.clone();
+    signed.signature = Bytes::from(vec![1; 65]);
+    assert_eq!(signed.hash(entry_point, 1), op.hash(entry_point, 1));
+}
+
+#[test]
+fn test_sign_is_personal_message_over_op_hash() {
+    let owner = LocalWallet::from_bytes(&[0x42; 32]).unwrap();
+    let entry_point: Address = ENTRY_POINT.parse().unwrap();
+    let mut op = fixture_op();
+    let hash = op.sign(&owner, entry_point, 1).unwrap();
+    assert_eq!(format!("{:?}", hash), OP_HASH);
+    assert_eq!(op.signature.len(), 65);
+
+    let signature = Signature::try_from(op.signature.as_ref()).unwrap();
+    assert!(signature.v == 27 || signature.v == 28);
+    let digest: H256 = ethers::utils::hash_message(hash.as_bytes());
+    assert_eq!(signature.recover(digest).unwrap(), owner.address());
+}
+
+#[test]
+fn test_execute_calldata_encoding() {
+    let transfer = hex::decode(
+        "a9059cbb000000000000000000000000111111111111111111111111111111111111111100000000000000000000000000000000000000000000000000000000000003e8",
+    )
+    .unwrap();
+    let dest: Address = "0x000000000000000000000000000000000000dEaD".parse().unwrap();
+    let calldata = encode_execute(dest, ethers::utils::parse_ether("1").unwrap(), &transfer);
+    assert_eq!(
+        hex::encode(&calldata),
+        "b61d27f6\
+         000000000000000000000000000000000000000000000000000000000000dead\
+         0000000000000000000000000000000000000000000000000de0b6b3a7640000\
+         0000000000000000000000000000000000000000000000000000000000000060\
+         0000000000000000000000000000000000000000000000000000000000000044\
+         a9059cbb0000000000000000000000001111111111111111111111111111111111111111\
+         00000000000000000000000000000000000000000000000000000000000003e8\
+         00000000000000000000000000000000000000000000000000000000"
+    );
+    // 空 data：偏移 + 长度 0
+    assert_eq!(encode_execute(dest, U256::zero(), &[]).len(), 4 + 4 * 32);
+}
+
+fn rpc_result(result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": 1, "result": result })
+}
+
+struct Harness {
+    app: TestServer,
+    server: WalletServer,
+    _node: MockServer,
+    bundler: MockServer,
+    wallet_id: String,
+    _dir: tempfile::TempDir,
+}
+
+async fn harness() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let node = MockServer::start_async().await;
+    let bundler = MockServer::start_async().await;
+
+    let mut config = util::memory_config();
+    config.blockchain.networks.get_mut("eth").unwrap().rpc_url = node.base_url();
+    config.account_abstraction.bundler_urls.insert("eth".to_string(), bundler.base_url());
+    // 后台跟踪任务不参与本测试，由查询接口按需刷新
+    config.account_abstraction.receipt_poll_secs = 3600;
+
+    let server = util::test_server(dir.path(), config, None).await;
+    let user_id = util::sign_in(&server, "aa@example.com", SESSION).await;
+    let wallet_id = util::create_wallet(&server, "aa_wallet", PASSWORD).await;
+    let owner = server.wallet_manager.ethereum_signer("aa_wallet", PASSWORD).await.unwrap();
+    server.user_db.link_wallet(&user_id, "aa_wallet", &format!("{:?}", owner.address()), None).await.unwrap();
+
+    // 节点：账户尚未部署（反事实address，无代码）
+    node.mock_async(|when, then| {
+        when.method(Method::POST).body_contains("eth_call");
+        then.status(200).json_body(rpc_result(json!(format!("0x{:0>64}", &ACCOUNT[2..]))));
+    })
+    .await;
+    node.mock_async(|when, then| {
+        when.method(Method::POST).body_contains("eth_getCode");
+        then.status(200).json_body(rpc_result(json!("0x")));
+    })
+    .await;
+    node.mock_async(|when, then| {
+        when.method(Method::POST).body_contains("eth_gasPrice");
+        then.status(200).json_body(rpc_result(json!("0x77359400")));
+    })
+    .await;
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { app, server, _node: node, bundler, wallet_id, _dir: dir }
+}
+
+fn send_body() -> Value {
+    json!({
+        "network": "eth",
+        "to": "0x000000000000000000000000000000000000dEaD",
+        "value": "0.01",
+        "password": PASSWORD,
+        "paymaster_and_data": "0xabcdef",
+    })
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_bundler_round_trip_pending_to_included() {
+    let h = harness().await;
+    let estimate = h
+        .bundler
+        .mock_async(|when, then| {
+            when.method(Method::POST).body_contains("eth_estimateUserOperationGas");
+            then.status(200).json_body(rpc_result(json!({
+                "preVerificationGas": "0xb708",
+                "verificationGasLimit": "0x5f370",
+                "callGasLimit": 35000,
+            })));
+        })
+        .await;
+    // 首次使用：initCode 指向 factory，paymasterAndData 原样附加，gas 来自估算
+    let send = h
+        .bundler
+        .mock_async(|when, then| {
+            when.method(Method::POST)
+                .body_contains("eth_sendUserOperation")
+                .body_contains(format!("\"initCode\":\"{}", FACTORY.to_lowercase()))
+                .body_contains("\"paymasterAndData\":\"0xabcdef\"")
+                .body_contains("\"verificationGasLimit\":\"0x5f370\"");
+            then.status(200).json_body(rpc_result(json!(OP_HASH)));
+        })
+        .await;
+
+    let res = h
+        .app
+        .post("/api/wallets/aa_wallet/aa/send")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&send_body())
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["user_op_hash"], OP_HASH);
+    assert_eq!(body["sender"], ACCOUNT);
+    assert_eq!(body["nonce"], "0");
+    assert_eq!(body["account_deployed"], false);
+    assert_eq!(body["status"], "pending");
+    estimate.assert_async().await;
+    send.assert_async().await;
+
+    let pending = h
+        .bundler
+        .mock_async(|when, then| {
+            when.method(Method::POST).body_contains("eth_getUserOperationReceipt");
+            then.status(200).json_body(rpc_result(Value::Null));
+        })
+        .await;
+    let path = format!("/api/wallets/aa_wallet/aa/operations/{}", OP_HASH);
+    let body: Value = h.app.get(&path).add_header("Authorization", format!("Bearer {}", SESSION)).await.json();
+    assert_eq!(body["status"], "pending");
+    assert!(body["tx_hash"].is_null());
+    assert!(h.server.storage.get_wallet_transactions(&h.wallet_id).await.unwrap().is_empty());
+    pending.delete_async().await;
+
+    h.bundler
+        .mock_async(|when, then| {
+            when.method(Method::POST).body_contains("eth_getUserOperationReceipt");
+            then.status(200).json_body(rpc_result(json!({
+                "userOpHash": OP_HASH,
+                "sender": ACCOUNT,
+                "nonce": "0x0",
+                "success": true,
+                "actualGasCost": "0x2386f26fc10000",
+                "actualGasUsed": "0x1d4c0",
+                "logs": [],
+                "receipt": { "transactionHash": BUNDLE_TX, "blockNumber": "0x12d687" },
+            })));
+        })
+        .await;
+    let body: Value = h.app.get(&path).add_header("Authorization", format!("Bearer {}", SESSION)).await.json();
+    assert_eq!(body["status"], "included");
+    assert_eq!(body["tx_hash"], BUNDLE_TX);
+    assert_eq!(body["actual_gas_cost"], "10000000000000000");
+
+    let records = h.server.storage.get_wallet_transactions(&h.wallet_id).await.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].id, OP_HASH);
+    assert_eq!(records[0].tx_hash, BUNDLE_TX);
+    assert_eq!(records[0].from_address, ACCOUNT);
+    assert_eq!(records[0].amount, "0.010000000000000000");
+    assert_eq!(records[0].fee, "0.010000000000000000");
+    assert_eq!(records[0].status, "confirmed");
+
+    // 已完成的 op 不再查询 bundler，也不会重复记账
+    let body: Value = h.app.get(&path).add_header("Authorization", format!("Bearer {}", SESSION)).await.json();
+    assert_eq!(body["status"], "included");
+    assert_eq!(h.server.storage.get_wallet_transactions(&h.wallet_id).await.unwrap().len(), 1);
+
+    h.app.get(&path).await.assert_status_unauthorized();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_bundler_errors_map_to_structured_codes() {
+    let h = harness().await;
+    let estimate = h
+        .bundler
+        .mock_async(|when, then| {
+            when.method(Method::POST).body_contains("eth_estimateUserOperationGas");
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32500, "message": "UserOperation reverted during simulation with reason: AA21 didn't pay prefund" },
+            }));
+        })
+        .await;
+    let res = h
+        .app
+        .post("/api/wallets/aa_wallet/aa/send")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&send_body())
+        .await;
+    res.assert_status(axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "AA21_PREFUND_NOT_PAID");
+    estimate.delete_async().await;
+
+    h.bundler
+        .mock_async(|when, then| {
+            when.method(Method::POST).body_contains("eth_estimateUserOperationGas");
+            then.status(200).json_body(rpc_result(json!({
+                "preVerificationGas": "0xb708",
+                "verificationGasLimit": "0x5f370",
+                "callGasLimit": "0x88b8",
+            })));
+        })
+        .await;
+    h.bundler
+        .mock_async(|when, then| {
+            when.method(Method::POST).body_contains("eth_sendUserOperation");
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32507, "message": "FailedOp(0, \"AA24 signature error\")" },
+            }));
+        })
+        .await;
+    let res = h
+        .app
+        .post("/api/wallets/aa_wallet/aa/send")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&send_body())
+        .await;
+    res.assert_status(axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(res.json::<Value>()["error"]["code"], "AA24_SIGNATURE_ERROR");
+
+    // 没有配置 bundler 的network
+    let res = h
+        .app
+        .post("/api/wallets/aa_wallet/aa/send")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "network": "polygon", "to": "0x000000000000000000000000000000000000dEaD", "password": PASSWORD }))
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "AA_NOT_CONFIGURED");
+
+    let res = h
+        .app
+        .post("/api/wallets/aa_wallet/aa/send")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "network": "eth", "to": "0x000000000000000000000000000000000000dEaD", "password": PASSWORD, "data": "abcd" }))
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_HEX");
+}
@@ -0,0 +1,129 @@
+//! 错误目录一致性：真实 handler 返回的 code 必须在目录里，且 HTTP 状态与
+//! `retryable` 与目录声明一致；`GET /api/errors` 列出全部错误码。
+//! 打开 `test-env` 时再经注入端点把目录里的每个错误码走一遍响应中间件。
+
+use axum_test::{TestResponse, TestServer};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+use defi_hot_wallet::api::error_catalog::ApiErrorCode;
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "error-catalog-admin-key";
+const TO: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+
+async fn build() -> (TestServer, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    (TestServer::new(server.create_router().await).unwrap(), dir)
+}
+
+/// 响应的 code 在目录中，状态与 retryable 和目录一致
+fn assert_conforms(res: &TestResponse, expected: &str) {
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], expected, "{}", body);
+    let entry = ApiErrorCode::from_code(expected).expect("code missing from the catalog").entry();
+    assert_eq!(res.status_code().as_u16(), entry.status, "{} sent with an undeclared status", expected);
+    assert_eq!(body["error"]["retryable"], entry.retryable, "{}", expected);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_handler_errors_match_catalog() {
+    let (app, _dir) = build().await;
+
+    let res = app.get("/api/events").await;
+    assert_conforms(&res, "AUTH_FAILED");
+
+    let res = app.get("/api/wallets/ghost/address_book/export").add_header("Authorization", API_KEY).await;
+    assert_conforms(&res, "WALLET_NOT_FOUND");
+
+    let res = app.get("/api/transactions/not-a-hash/status").add_header("Authorization", API_KEY).await;
+    assert_conforms(&res, "INVALID_TX_HASH");
+
+    let res = app
+        .post("/api/wallets/ghost/send_multi_sig")
+        .add_header("Authorization", API_KEY)
+        .json(&json!({ "to": TO, "amount": "1", "network": "btc", "signatures": ["sig1", "sig2"] }))
+        .await;
+    assert_conforms(&res, "UNSUPPORTED_NETWORK");
+
+    // 无法解析的 body 与字段缺失分属两个 code
+    let res = app
+        .post("/api/bridge")
+        .add_header("Authorization", API_KEY)
+        .content_type("application/json")
+        .bytes("{not json".into())
+        .await;
+    assert_conforms(&res, "INVALID_REQUEST");
+    let res = app.post("/api/bridge").add_header("Authorization", API_KEY).json(&json!({})).await;
+    assert_conforms(&res, "INVALID_REQUEST_BODY");
+
+    let res = app.post("/api/admin/jobs/no_such_job/run").add_header("Authorization", API_KEY).await;
+    assert_conforms(&res, "JOB_NOT_FOUND");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_catalog_endpoint_lists_every_code() {
+    let (app, _dir) = build().await;
+
+    // 没有凭据：admin key 与wallet调用方都不成立，按会话缺失处理
+    let res = app.get("/api/errors").await;
+    assert_conforms(&res, "AUTH_REQUIRED");
+
+    let res = app.get("/api/errors").add_header("Authorization", API_KEY).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    let errors = body["errors"].as_array().unwrap();
+    assert_eq!(errors.len(), ApiErrorCode::ALL.len());
+    let listed: HashSet<&str> = errors.iter().map(|e| e["code"].as_str().unwrap()).collect();
+    for code in ApiErrorCode::ALL {
+        assert!(listed.contains(code.as_str()), "{} not listed", code);
+    }
+
+    let not_found = errors.iter().find(|e| e["code"] == "WALLET_NOT_FOUND").unwrap();
+    assert_eq!(not_found["status"], 404);
+    assert_eq!(not_found["retryable"], false);
+    assert_eq!(not_found["message_key"], "error-wallet-not-found");
+    let busy = errors.iter().find(|e| e["code"] == "SERVER_BUSY").unwrap();
+    assert_eq!(busy["status"], 429);
+    assert_eq!(busy["retryable"], true);
+    // 没有消息键的条目不输出该字段
+    let rpc = errors.iter().find(|e| e["code"] == "RPC_ERROR").unwrap();
+    assert!(rpc.get("message_key").is_none());
+}
+
+#[cfg(feature = "test-env")]
+#[tokio::test]
+#[serial_test::serial]
+async fn test_every_catalog_code_round_trips() {
+    let (app, _dir) = build().await;
+    for code in ApiErrorCode::ALL {
+        let res = app.get(&format!("/api/test/errors/{}", code)).await;
+        assert_conforms(&res, code.as_str());
+    }
+
+    let res = app.get("/api/test/errors/NO_SUCH_CODE").await;
+    res.assert_status_not_found();
+}
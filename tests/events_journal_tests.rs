@@ -0,0 +1,311 @@
+//! events_journal：顺序、无间隙、脱敏，以及 `/api/events` 长轮询
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum_test::TestServer;
+use chrono::Utc;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::bridge::{BridgeTransaction, BridgeTransactionStatus};
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::monitoring::{
+    SecurityEvent, SecurityEventType, SecurityMonitor, SecuritySeverity, WalletMetrics,
+};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{journal_events, NewJournalEvent, TransactionRecord, WalletStorage};
+
+const API_KEY: &str = "events-journal-test-key-0123456789";
+
+async fn file_storage(dir: &TempDir) -> WalletStorage {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("journal.db").display());
+    WalletStorage::new_with_url(&url).await.unwrap()
+}
+
+fn assert_gapless(seqs: &[i64]) {
+    for (i, seq) in seqs.iter().enumerate() {
+        assert_eq!(*seq, i as i64 + 1, "journal sequence has a gap: {:?}", seqs);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn test_concurrent_wallet_creations_are_gapless() {
+    let dir = TempDir::new().unwrap();
+    let storage = Arc::new(file_storage(&dir).await);
+
+    // 120 attempts over 90 names: the 30 duplicates fail and must leave no trace
+    let handles: Vec<_> = (0..120)
+        .map(|i| {
+            let storage = storage.clone();
+            tokio::spawn(async move { storage.store_wallet(&format!("w{}", i % 90), b"blob", false).await.is_ok() })
+        })
+        .collect();
+    let mut committed = 0;
+    for h in handles {
+        if h.await.unwrap() {
+            committed += 1;
+        }
+    }
+    assert_eq!(committed, 90);
+
+    let events = storage.journal_events(0, 1000).await.unwrap();
+    let seqs: Vec<i64> = events.iter().map(|e| e.seq).collect();
+    assert_gapless(&seqs);
+    assert_eq!(events.len(), committed);
+    assert!(events.iter().all(|e| e.event_type == journal_events::WALLET_CREATED));
+
+    let names: HashSet<&str> = events.iter().map(|e| e.payload["name"].as_str().unwrap()).collect();
+    assert_eq!(names.len(), 90);
+    let ids: HashSet<&str> = events.iter().map(|e| e.entity_id.as_str()).collect();
+    let stored: HashSet<String> = storage.list_wallets().await.unwrap().into_iter().map(|w| w.id).collect();
+    assert_eq!(ids.len(), stored.len());
+    assert!(ids.iter().all(|id| stored.contains(*id)));
+}
+
+#[tokio::test]
+async fn test_lifecycle_changes_are_journaled_in_order() {
+    let dir = TempDir::new().unwrap();
+    let storage = file_storage(&dir).await;
+
+    storage.store_wallet("alpha", b"blob", false).await.unwrap();
+    storage.store_wallet("scratch", b"blob", false).await.unwrap();
+    storage.delete_wallet("scratch").await.unwrap();
+    let wallet_id = storage.list_wallets().await.unwrap()[0].id.clone();
+    let tx = TransactionRecord {
+        id: "tx-1".to_string(),
+        wallet_id: wallet_id.clone(),
+        tx_hash: format!("0x{:064x}", 1),
+        network: "eth".to_string(),
+        from_address: "0x1111111111111111111111111111111111111111".to_string(),
+        to_address: "0x2222222222222222222222222222222222222222".to_string(),
+        amount: "1.5".to_string(),
+        fee: "0.001".to_string(),
+        status: "pending".to_string(),
+        created_at: Utc::now(),
+        confirmed_at: None,
+        integrity_hash: String::new(),
+        fee_actual: None,
+        block_number: None,
+    };
+    storage.store_transaction(&tx).await.unwrap();
+    storage.update_transaction_status("tx-1", "pending", None).await.unwrap();
+    storage.update_transaction_status("tx-1", "confirmed", Some(Utc::now())).await.unwrap();
+    storage.rename_wallet("alpha", "beta").await.unwrap();
+    assert!(storage.rename_wallet("alpha", "gamma").await.is_err());
+    storage.rotation_insert_version("signing:beta", 1, "kid-1").await.unwrap();
+    storage.rotation_mark_retired("signing:beta", 1).await.unwrap();
+
+    let events = storage.journal_events(0, 100).await.unwrap();
+    let types: Vec<&str> = events.iter().map(|e| e.event_type.as_str()).collect();
+    assert_eq!(
+        types,
+        vec![
+            journal_events::WALLET_CREATED,
+            journal_events::WALLET_CREATED,
+            journal_events::WALLET_DELETED,
+            journal_events::TRANSACTION_CREATED,
+            // the no-op status update is not an event
+            journal_events::TRANSACTION_STATUS_CHANGED,
+            journal_events::WALLET_RENAMED,
+            journal_events::KEY_ROTATED,
+        ]
+    );
+    assert_gapless(&events.iter().map(|e| e.seq).collect::<Vec<_>>());
+    assert_eq!(events[1].entity_id, events[2].entity_id);
+    assert_eq!(events[3].payload["wallet_id"], wallet_id.as_str());
+    assert_eq!(events[4].payload["from_status"], "pending");
+    assert_eq!(events[4].payload["to_status"], "confirmed");
+    assert_eq!(events[5].entity_id, wallet_id);
+    assert_eq!(events[5].payload, json!({ "old_name": "alpha", "new_name": "beta" }));
+
+    // paging by cursor
+    let page = storage.journal_events(events[1].seq, 2).await.unwrap();
+    assert_eq!(page.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![events[2].seq, events[3].seq]);
+}
+
+#[tokio::test]
+async fn test_bridge_status_change_is_journaled() {
+    let dir = TempDir::new().unwrap();
+    let storage = file_storage(&dir).await;
+    // unknown bridge transfer: nothing changed, nothing journaled
+    storage
+        .update_bridge_transaction_status("missing", BridgeTransactionStatus::Completed, None)
+        .await
+        .unwrap();
+    assert!(storage.journal_events(0, 10).await.unwrap().is_empty());
+
+    let now = Utc::now();
+    storage
+        .store_bridge_transaction(&BridgeTransaction {
+            id: "bridge-1".to_string(),
+            from_wallet: "alpha".to_string(),
+            from_chain: "eth".to_string(),
+            to_chain: "polygon".to_string(),
+            token: "USDC".to_string(),
+            amount: "10".to_string(),
+            status: BridgeTransactionStatus::Initiated,
+            source_tx_hash: None,
+            destination_tx_hash: None,
+            created_at: now,
+            updated_at: now,
+            fee_amount: None,
+            estimated_completion_time: None,
+            amount_minimal_source: None,
+            amount_minimal_dest: None,
+            token_address_source: None,
+            token_address_dest: None,
+        })
+        .await
+        .unwrap();
+    storage
+        .update_bridge_transaction_status(
+            "bridge-1",
+            BridgeTransactionStatus::InTransit,
+            Some("0xsource".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let events = storage.journal_events(0, 10).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, journal_events::BRIDGE_STATUS_CHANGED);
+    assert_eq!(events[0].entity_id, "bridge-1");
+    assert_eq!(events[0].payload["source_tx_hash"], "0xsource");
+}
+
+#[tokio::test]
+async fn test_payloads_are_redacted() {
+    let dir = TempDir::new().unwrap();
+    let storage = file_storage(&dir).await;
+    storage
+        .record_event(&NewJournalEvent {
+            event_type: journal_events::LIMITS_CHANGED,
+            entity_type: "wallet",
+            entity_id: "w1",
+            payload: json!({
+                "daily_limit": "10",
+                "private_key": "0xdeadbeef",
+                "nested": [{ "Mnemonic": "abandon abandon", "note": "ok" }],
+                "encrypted_data": "AAAA",
+            }),
+        })
+        .await
+        .unwrap();
+    let event = &storage.journal_events(0, 10).await.unwrap()[0];
+    assert_eq!(
+        event.payload,
+        json!({
+            "daily_limit": "10",
+            "private_key": "[REDACTED]",
+            "nested": [{ "Mnemonic": "[REDACTED]", "note": "ok" }],
+            "encrypted_data": "[REDACTED]",
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_only_high_severity_security_events_are_journaled() {
+    let dir = TempDir::new().unwrap();
+    let storage = Arc::new(file_storage(&dir).await);
+    let monitor = SecurityMonitor::new(Arc::new(WalletMetrics::new().unwrap())).with_journal(storage.clone());
+
+    for severity in [SecuritySeverity::Low, SecuritySeverity::Medium, SecuritySeverity::High, SecuritySeverity::Critical] {
+        monitor
+            .report_security_event(SecurityEvent {
+                event_type: SecurityEventType::UnauthorizedAccess,
+                description: "token=secret-token-value".to_string(),
+                severity,
+                timestamp: Utc::now(),
+                source_ip: Some("203.0.113.7".to_string()),
+                wallet_id: Some("w1".to_string()),
+            })
+            .await;
+    }
+
+    let events = storage.journal_events(0, 10).await.unwrap();
+    let severities: Vec<&str> = events.iter().map(|e| e.payload["severity"].as_str().unwrap()).collect();
+    assert_eq!(severities, vec!["HIGH", "CRITICAL"]);
+    for e in &events {
+        assert_eq!(e.event_type, journal_events::SECURITY_EVENT);
+        assert!(!e.payload.to_string().contains("secret-token-value"));
+    }
+}
+
+#[tokio::test]
+async fn test_events_endpoint_pages_and_long_polls() {
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    app.get("/api/events").await.assert_status_unauthorized();
+
+    for i in 0..3 {
+        storage
+            .record_event(&NewJournalEvent {
+                event_type: journal_events::LIMITS_CHANGED,
+                entity_type: "wallet",
+                entity_id: &format!("w{}", i),
+                payload: json!({ "i": i }),
+            })
+            .await
+            .unwrap();
+    }
+
+    let res = app.get("/api/events?after_seq=0&limit=2").add_header("Authorization", API_KEY).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["events"].as_array().unwrap().len(), 2);
+    assert_eq!(body["next_seq"], 2);
+
+    let res = app.get("/api/events?after_seq=2").add_header("Authorization", API_KEY).await;
+    let body: Value = res.json();
+    assert_eq!(body["events"][0]["seq"], 3);
+    assert_eq!(body["next_seq"], 3);
+
+    // nothing new, no wait: returns at once with the cursor unchanged
+    let body: Value = app.get("/api/events?after_seq=3").add_header("Authorization", API_KEY).await.json();
+    assert!(body["events"].as_array().unwrap().is_empty());
+    assert_eq!(body["next_seq"], 3);
+
+    // long poll wakes up as soon as an event commits
+    let started = Instant::now();
+    let poll = app.get("/api/events?after_seq=3&wait=20").add_header("Authorization", API_KEY);
+    let commit = async {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        storage
+            .record_event(&NewJournalEvent {
+                event_type: journal_events::LIMITS_CHANGED,
+                entity_type: "wallet",
+                entity_id: "w3",
+                payload: json!({}),
+            })
+            .await
+            .unwrap();
+    };
+    let (res, ()) = tokio::join!(async { poll.await }, commit);
+    assert!(started.elapsed() < Duration::from_secs(10));
+    let body: Value = res.json();
+    assert_eq!(body["events"][0]["seq"], 4);
+    assert_eq!(body["next_seq"], 4);
+}
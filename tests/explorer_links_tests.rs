@@ -0,0 +1,240 @@
+//! 区块浏览器链接：配置往返、模板校验、历史响应中的渲染结果与 `GET /api/networks`
+
+use axum_test::TestServer;
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{
+    BlockchainConfig, ExplorerUrlTemplate, NetworkConfig, StorageConfig, WalletConfig,
+};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::TransactionRecord;
+
+const API_KEY: &str = "explorer-links-test-key-0123456789";
+const FROM: &str = "0x1234567890123456789012345678901234567890";
+const TO: &str = "0x0987654321098765432109876543210987654321";
+
+fn network(name: &str, chain_id: u64, explorer: Option<&str>) -> NetworkConfig {
+    NetworkConfig {
+        name: name.to_string(),
+        rpc_url: "http://127.0.0.1:1".to_string(),
+        chain_id,
+        explorer_url_template: explorer.map(ExplorerUrlTemplate::etherscan_style),
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
+    }
+}
+
+fn config(networks: Vec<(&str, NetworkConfig)>) -> WalletConfig {
+    WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        blockchain: BlockchainConfig {
+            networks: networks.into_iter().map(|(k, v)| (k.to_string(), v)).collect::<HashMap<_, _>>(),
+        },
+        ..Default::default()
+    }
+}
+
+async fn server(config: WalletConfig) -> Result<WalletServer, WalletError> {
+    WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+}
+
+#[test]
+fn test_config_round_trip() {
+    let mut networks = BlockchainConfig { networks: HashMap::new() };
+    networks.networks.insert("eth".to_string(), network("Ethereum", 1, Some("https://etherscan.io/")));
+    networks.networks.insert("devnet".to_string(), network("Devnet", 1337, None));
+
+    let encoded = toml::to_string(&networks).unwrap();
+    assert!(encoded.contains("tx = \"https://etherscan.io/tx/{hash}\""), "{}", encoded);
+    // 未配置的network不写出空模板
+    assert_eq!(encoded.matches("explorer_url_template").count(), 1, "{}", encoded);
+
+    let decoded: BlockchainConfig = toml::from_str(&encoded).unwrap();
+    let eth = decoded.networks["eth"].explorer_url_template.as_ref().unwrap();
+    assert_eq!(eth, &ExplorerUrlTemplate::etherscan_style("https://etherscan.io"));
+    assert_eq!(eth.base(), "https://etherscan.io");
+    assert!(decoded.networks["devnet"].explorer_url_template.is_none());
+    decoded.validate().unwrap();
+
+    assert_eq!(
+        decoded.explorer_address_url("eth", TO).as_deref(),
+        Some("https://etherscan.io/address/0x0987654321098765432109876543210987654321")
+    );
+    assert_eq!(decoded.explorer_tx_url("devnet", "0xabc"), None);
+    assert_eq!(decoded.explorer_tx_url("unknown", "0xabc"), None);
+}
+
+#[test]
+fn test_legacy_explorer_url_still_links() {
+    // 模板引入前的配置只有浏览器根地址
+    let decoded: BlockchainConfig = toml::from_str(
+        r#"
+[networks.eth]
+name = "Ethereum"
+rpc_url = "https://eth.llamarpc.com"
+chain_id = 1
+explorer_url = "https://etherscan.io"
+"#,
+    )
+    .unwrap();
+    decoded.validate().unwrap();
+    assert_eq!(
+        decoded.explorer_tx_url("eth", "0xabc").as_deref(),
+        Some("https://etherscan.io/tx/0xabc")
+    );
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_bad_templates_rejected_at_startup() {
+    let cases = [
+        ("https://etherscan.io/tx/{txid}", "https://etherscan.io/address/{address}", "unknown placeholder"),
+        ("https://etherscan.io/tx/", "https://etherscan.io/address/{address}", "must contain {hash}"),
+        ("https://etherscan.io/tx/{hash}", "https://etherscan.io/address/{hash}", "unknown placeholder"),
+        ("etherscan.io/tx/{hash}", "https://etherscan.io/address/{address}", "http(s) URL"),
+    ];
+    for (tx, address, expected) in cases {
+        let mut eth = network("Ethereum", 1, None);
+        eth.explorer_url_template = Some(ExplorerUrlTemplate { tx: tx.to_string(), address: address.to_string() });
+        match server(config(vec![("eth", eth)])).await {
+            Err(WalletError::ConfigError(msg)) => {
+                assert!(msg.contains("blockchain.networks.eth.explorer_url_template"), "{}", msg);
+                assert!(msg.contains(expected), "{}: {}", expected, msg);
+            }
+            Err(other) => panic!("expected ConfigError, got {}", other),
+            Ok(_) => panic!("template {} / {} should be rejected", tx, address),
+        }
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_history_renders_links_and_omits_them_without_template() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let server = server(config(vec![
+        ("eth", network("Ethereum", 1, Some("https://etherscan.io"))),
+        ("devnet", network("Devnet", 1337, None)),
+    ]))
+    .await
+    .unwrap();
+    // 历史接口只认 WalletManager 中的wallet（非托管的 POST /api/wallets 不会登记到这里）
+    server.wallet_manager.create_wallet("explorer_wallet", "explorer-pass", false).await.unwrap();
+    server.storage.store_wallet("explorer_wallet", b"blob", false).await.unwrap();
+    let wallet_id = server.storage.wallet_id("explorer_wallet").await.unwrap().unwrap();
+    for (i, network) in ["eth", "devnet"].iter().enumerate() {
+        server
+            .storage
+            .store_transaction(&TransactionRecord {
+                id: format!("explorer-tx-{}", i),
+                wallet_id: wallet_id.clone(),
+                tx_hash: format!("0x{:064x}", i + 1),
+                network: network.to_string(),
+                from_address: FROM.to_string(),
+                to_address: TO.to_string(),
+                amount: "1.0".to_string(),
+                fee: "0.001".to_string(),
+                status: "pending".to_string(),
+                created_at: Utc::now() - chrono::Duration::seconds(i as i64),
+                confirmed_at: None,
+                integrity_hash: String::new(),
+                fee_actual: None,
+                block_number: None,
+            })
+            .await
+            .unwrap();
+    }
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let body: Value = app
+        .get("/api/wallets/explorer_wallet/history")
+        .add_header("Authorization", API_KEY)
+        .await
+        .json();
+    let transactions = body["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 2);
+
+    let eth = transactions.iter().find(|t| t["network"] == "eth").unwrap();
+    assert_eq!(eth["hash"], format!("0x{:064x}", 1));
+    assert_eq!(eth["status"], "pending");
+    assert_eq!(eth["explorer_url"], format!("https://etherscan.io/tx/0x{:064x}", 1));
+    assert_eq!(eth["from_explorer_url"], format!("https://etherscan.io/address/{}", FROM));
+    assert_eq!(eth["to_explorer_url"], format!("https://etherscan.io/address/{}", TO));
+
+    let devnet = transactions.iter().find(|t| t["network"] == "devnet").unwrap();
+    for field in ["explorer_url", "from_explorer_url", "to_explorer_url"] {
+        assert!(devnet.get(field).is_none(), "{} should be omitted: {}", field, devnet);
+    }
+
+    // 状态查询通过本地记录找到 network
+    let status: Value = app
+        .get(&format!("/api/transactions/0x{:064x}/status", 1))
+        .add_header("Authorization", API_KEY)
+        .await
+        .json();
+    assert_eq!(status["network"], "eth");
+    assert_eq!(status["explorer_url"], format!("https://etherscan.io/tx/0x{:064x}", 1));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_networks_listing_shape() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let server = server(config(vec![
+        ("eth", network("Ethereum Mainnet", 1, Some("https://etherscan.io"))),
+        ("polygon", network("Polygon Mainnet", 137, Some("https://polygonscan.com"))),
+        ("devnet", network("Devnet", 1337, None)),
+    ]))
+    .await
+    .unwrap();
+    for _ in 0..5 {
+        server.circuit_breaker.record_failure("polygon");
+    }
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    app.get("/api/networks").await.assert_status_unauthorized();
+
+    let body: Value = app.get("/api/networks").add_header("Authorization", API_KEY).await.json();
+    let networks = body["networks"].as_array().unwrap();
+    let ids: Vec<&str> = networks.iter().map(|n| n["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, vec!["devnet", "eth", "polygon"]);
+
+    assert_eq!(
+        networks[1],
+        json!({
+            "id": "eth",
+            "name": "Ethereum Mainnet",
+            "chain_id": 1,
+            "symbol": "ETH",
+            "explorer_base": "https://etherscan.io",
+            "explorer_url_template": {
+                "tx": "https://etherscan.io/tx/{hash}",
+                "address": "https://etherscan.io/address/{address}"
+            },
+            "required_confirmations": 12,
+            "health": "ok"
+        })
+    );
+    assert_eq!(networks[2]["health"], "down");
+    assert_eq!(networks[2]["required_confirmations"], 128);
+    assert!(networks[0].get("explorer_base").is_none());
+    assert!(networks[0].get("explorer_url_template").is_none());
+}
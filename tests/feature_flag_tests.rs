@@ -0,0 +1,243 @@
+//! 运行时功能开关：wallet规则优先于全局规则、百分比灰度的稳定分桶、cache epoch
+//! 跨实例失效、未声明开关的拒绝，以及 `/api/admin/flags` 的审计与 journal
+//!
+//! 两个 WalletStorage 句柄共享同一个内存数据库，模拟两个实例；时间由 FixedClock 推进。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::clock::FixedClock;
+use defi_hot_wallet::core::config::{DeadmanConfig, FeatureFlagsConfig, StorageConfig, WalletConfig};
+use defi_hot_wallet::feature_flags::{bucket, FeatureFlags, Flag, FlagError};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::journal_events::FEATURE_FLAG_CHANGED;
+use defi_hot_wallet::storage::WalletStorage;
+
+const NOW: i64 = 1_700_000_000;
+const API_KEY: &str = "feature-flag-test-key-0123456789";
+
+struct Pair {
+    a: FeatureFlags,
+    b: FeatureFlags,
+    storage: Arc<WalletStorage>,
+    clock: Arc<FixedClock>,
+}
+
+async fn open(url: &str, clock: &Arc<FixedClock>) -> Arc<WalletStorage> {
+    Arc::new(WalletStorage::new_with_url(url).await.unwrap().with_clock(clock.clone()))
+}
+
+async fn two_instances() -> Pair {
+    let url = format!("sqlite://file:feature-flags-{}?mode=memory&cache=shared", uuid::Uuid::new_v4());
+    let clock = Arc::new(FixedClock::at(NOW));
+    let storage = open(&url, &clock).await;
+    let interval = Duration::from_secs(1);
+    let a = FeatureFlags::new(storage.clone()).with_epoch_check_interval(interval);
+    let b = FeatureFlags::new(open(&url, &clock).await).with_epoch_check_interval(interval);
+    Pair { a, b, storage, clock }
+}
+
+#[tokio::test]
+async fn test_wallet_rule_overrides_global_rule() {
+    let p = two_instances().await;
+    let flag = Flag::AnomalyBlocking;
+    assert!(p.a.is_enabled(flag, Some("vip")).await);
+
+    p.a.set(flag, None, false, None, "ops").await.unwrap();
+    assert!(!p.a.is_enabled(flag, Some("vip")).await);
+    assert!(!p.a.is_enabled(flag, None).await);
+
+    p.a.set(flag, Some("vip"), true, None, "ops").await.unwrap();
+    assert!(p.a.is_enabled(flag, Some("vip")).await);
+    assert!(!p.a.is_enabled(flag, Some("payroll")).await);
+    // 其他开关不受影响
+    assert!(p.a.is_enabled(Flag::AccountAbstraction, Some("payroll")).await);
+
+    // wallet规则跟随改名，删除后回落到全局规则
+    p.storage.store_wallet("vip", &[7u8; 64], false).await.unwrap();
+    p.storage.rename_wallet("vip", "vip-2").await.unwrap();
+    p.clock.advance(chrono::Duration::seconds(1));
+    assert!(p.a.is_enabled(flag, Some("vip-2")).await);
+    assert!(!p.a.is_enabled(flag, Some("vip")).await);
+    assert!(p.a.clear(flag, Some("vip-2"), "ops").await.unwrap());
+    assert!(!p.a.is_enabled(flag, Some("vip-2")).await);
+    assert!(!p.a.clear(flag, Some("vip-2"), "ops").await.unwrap());
+
+    // 删除全局规则后回到默认值
+    p.a.clear(flag, None, "ops").await.unwrap();
+    assert!(p.a.is_enabled(flag, Some("vip-2")).await);
+
+    let events = p.storage.journal_events(0, 100).await.unwrap();
+    let changes: Vec<_> = events.iter().filter(|e| e.event_type == FEATURE_FLAG_CHANGED).collect();
+    assert_eq!(changes.len(), 4);
+    assert!(changes.iter().all(|e| e.entity_type == "feature_flag" && e.entity_id == "anomaly_blocking"));
+    assert_eq!(changes[1].payload["wallet_id"], "vip");
+    assert_eq!(changes[3].payload["cleared"], true);
+}
+
+#[tokio::test]
+async fn test_percentage_rollout_is_deterministic() {
+    let p = two_instances().await;
+    let flag = Flag::AccountAbstraction;
+    p.a.set(flag, None, true, Some(30), "ops").await.unwrap();
+    p.clock.advance(chrono::Duration::seconds(1));
+
+    let wallets: Vec<String> = (0..500).map(|i| format!("wallet-{}", i)).collect();
+    let mut on_a = Vec::new();
+    let mut on_b = Vec::new();
+    for wallet in &wallets {
+        if p.a.is_enabled(flag, Some(wallet)).await {
+            on_a.push(wallet.clone());
+        }
+        if p.b.is_enabled(flag, Some(wallet)).await {
+            on_b.push(wallet.clone());
+        }
+    }
+    // 两个实例对同一wallet的判定一致，且只取决于分桶
+    assert_eq!(on_a, on_b);
+    assert!(on_a.iter().all(|w| bucket(flag, w) < 30));
+    assert!((100..200).contains(&on_a.len()), "{} of 500 wallets in a 30% rollout", on_a.len());
+    // 部分灰度对不针对wallet的调用关闭
+    assert!(!p.a.is_enabled(flag, None).await);
+
+    // 分桶按开关区分：另一个开关的 30% 不是同一批wallet
+    let other: Vec<&String> = wallets.iter().filter(|w| bucket(Flag::DeadmanSwitch, w) < 30).collect();
+    assert_ne!(other.len(), 0);
+    assert!(other.iter().any(|w| !on_a.contains(w)));
+
+    // 只有全局规则可以灰度
+    let err = p.a.set(flag, Some("wallet-1"), true, Some(10), "ops").await.unwrap_err();
+    assert!(matches!(err, FlagError::InvalidRule(_)));
+    let err = p.a.set(flag, None, true, Some(101), "ops").await.unwrap_err();
+    assert!(matches!(err, FlagError::InvalidRule(_)));
+}
+
+#[tokio::test]
+async fn test_changes_propagate_across_storage_handles() {
+    let p = two_instances().await;
+    let flag = Flag::DeadmanSwitch;
+    assert!(p.b.is_enabled(flag, Some("vault")).await);
+
+    p.a.set(flag, Some("vault"), false, None, "ops").await.unwrap();
+    // 本句柄的改动立即生效；另一实例在检查间隔内仍用缓存
+    assert!(!p.a.is_enabled(flag, Some("vault")).await);
+    assert!(p.b.is_enabled(flag, Some("vault")).await);
+
+    p.clock.advance(chrono::Duration::seconds(1));
+    assert!(!p.b.is_enabled(flag, Some("vault")).await);
+    // a 也在此刻重新检查，下面 b 的改动落在 a 的检查间隔内
+    assert!(!p.a.is_enabled(flag, Some("vault")).await);
+
+    // 反方向同样传播
+    p.b.set(flag, Some("vault"), true, None, "ops").await.unwrap();
+    assert!(!p.a.is_enabled(flag, Some("vault")).await);
+    p.clock.advance(chrono::Duration::seconds(1));
+    assert!(p.a.is_enabled(flag, Some("vault")).await);
+    assert!(p.storage.feature_flags_epoch().await.unwrap() >= 2);
+}
+
+#[tokio::test]
+async fn test_config_defaults_and_undeclared_flags() {
+    let storage = Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap());
+
+    // 子系统原有的配置开关成为默认值
+    let mut config = WalletConfig::default();
+    config.security.deadman = DeadmanConfig { enabled: false, ..Default::default() };
+    let flags = FeatureFlags::from_config(&config, storage.clone()).unwrap();
+    assert!(!flags.default_for(Flag::DeadmanSwitch));
+    assert!(!flags.is_enabled(Flag::DeadmanSwitch, Some("vault")).await);
+    assert!(flags.default_for(Flag::AnomalyBlocking));
+
+    config.feature_flags =
+        FeatureFlagsConfig { defaults: HashMap::from([("anomaly_blocking".to_string(), false)]), ..Default::default() };
+    let flags = FeatureFlags::from_config(&config, storage.clone()).unwrap();
+    assert!(!flags.is_enabled(Flag::AnomalyBlocking, None).await);
+
+    config.feature_flags.defaults.insert("auto_approve_everything".to_string(), true);
+    let err = FeatureFlags::from_config(&config, storage.clone()).err().unwrap();
+    assert!(matches!(err, FlagError::Unknown(name) if name == "auto_approve_everything"));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_flag_endpoints() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    let storage = server.storage.clone();
+    let flags = server.flags.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    app.get("/api/admin/flags").await.assert_status_unauthorized();
+    let body = json!({ "flag": "anomaly_blocking", "enabled": false });
+    app.put("/api/admin/flags").json(&body).await.assert_status_unauthorized();
+
+    let res = app.get("/api/admin/flags").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    let names: Vec<&str> = body["flags"].as_array().unwrap().iter().map(|f| f["flag"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["anomaly_blocking", "account_abstraction", "deadman_switch"]);
+    assert!(body["flags"].as_array().unwrap().iter().all(|f| f["enabled"] == true && f["rules"] == json!([])));
+
+    let put = |body: Value| app.put("/api/admin/flags").add_header("X-API-KEY", API_KEY).json(&body);
+    let res = put(json!({ "flag": "auto_approve_everything", "enabled": true })).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "UNKNOWN_FEATURE_FLAG");
+    let rollout = json!({ "flag": "account_abstraction", "wallet_id": "ops", "enabled": true, "rollout_percent": 5 });
+    let res = put(rollout).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_FLAG_RULE");
+    let bad_wallet = json!({ "flag": "account_abstraction", "wallet_id": "../etc", "enabled": true });
+    put(bad_wallet).await.assert_status_bad_request();
+
+    let res = put(json!({ "flag": "account_abstraction", "enabled": false, "updated_by": "alice" })).await;
+    res.assert_status_ok();
+    let state: Value = res.json();
+    assert_eq!((state["enabled"].as_bool(), state["default"].as_bool()), (Some(false), Some(true)));
+    assert_eq!(state["rules"][0]["updated_by"], "alice");
+    assert!(!flags.is_enabled(Flag::AccountAbstraction, Some("ops")).await);
+
+    let res = put(json!({ "flag": "account_abstraction", "wallet_id": "ops", "enabled": true })).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["enabled"], true);
+    let res = app.get("/api/admin/flags?wallet_id=payroll").add_header("X-API-KEY", API_KEY).await;
+    let body: Value = res.json();
+    let aa = body["flags"].as_array().unwrap().iter().find(|f| f["flag"] == "account_abstraction").unwrap().clone();
+    assert_eq!((aa["enabled"].as_bool(), aa["rules"].as_array().unwrap().len()), (Some(false), 2));
+
+    // 省略 enabled 删除规则
+    let res = put(json!({ "flag": "account_abstraction", "wallet_id": "ops" })).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["enabled"], false);
+
+    let events = storage.journal_events(0, 100).await.unwrap();
+    assert_eq!(events.iter().filter(|e| e.event_type == FEATURE_FLAG_CHANGED).count(), 3);
+    let global = storage.get_audit_logs(Some("system")).await.unwrap();
+    let by_alice = |a: &&defi_hot_wallet::storage::AuditLog| a.details.as_deref().unwrap_or("").contains("alice");
+    assert!(global.iter().filter(by_alice).any(|a| a.action == "feature_flag.updated"));
+    let scoped = storage.get_audit_logs(Some("ops")).await.unwrap();
+    let actions: Vec<&str> = scoped.iter().map(|a| a.action.as_str()).collect();
+    assert!(actions.contains(&"feature_flag.updated") && actions.contains(&"feature_flag.cleared"));
+}
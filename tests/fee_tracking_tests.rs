@@ -0,0 +1,326 @@
+//! 手续费记录：按天聚合、回执补全与 `GET /api/analytics/fees`
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use chrono::{TimeZone, Utc};
+use ethers::signers::LocalWallet;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionReceipt, TransactionRequest, H256, U256, U64};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{FeeTrackingConfig, StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::{BroadcastChain, SigningIntentLog};
+use defi_hot_wallet::ops::fee_tracking::{ConfirmationPoller, ConfirmationReport};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{FeeGrouping, NewFeeRecord, WalletStorage};
+
+const NETWORK: &str = "eth";
+const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const API_KEY: &str = "fee-analytics-admin-key";
+const GWEI: u64 = 1_000_000_000;
+const BLOCK: u64 = 17_000_000;
+
+fn tx_hash(n: u8) -> String {
+    format!("{:?}", H256::repeat_byte(n))
+}
+
+struct Db {
+    storage: WalletStorage,
+    raw: sqlx::SqlitePool,
+    _dir: tempfile::TempDir,
+}
+
+async fn open_db() -> Db {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display());
+    // 落库wallet写审计记录，其 MAC 需要 WALLET_ENC_KEY
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let storage = WalletStorage::new_with_url(&url).await.unwrap();
+    storage.store_wallet("hot", b"blob", false).await.unwrap();
+    let raw = sqlx::SqlitePool::connect(&url).await.unwrap();
+    Db { storage, raw, _dir: dir }
+}
+
+impl Db {
+    /// 写入一笔发送（可选已确认），并把创建时间改到 `created_at`
+    async fn seed(&self, n: u8, fee: NewFeeRecord<'_>, receipt: Option<(u64, u64)>, created_at: i64) {
+        let hash = tx_hash(n);
+        self.storage.record_fee_broadcast(&NewFeeRecord { network: NETWORK, tx_hash: &hash, ..fee }).await.unwrap();
+        if let Some((gas_used, effective)) = receipt {
+            assert!(self.storage.complete_fee_record(NETWORK, &hash, gas_used, effective).await.unwrap());
+        }
+        sqlx::query("UPDATE fee_history SET created_at = ?1 WHERE tx_hash = ?2")
+            .bind(created_at)
+            .bind(&hash)
+            .execute(&self.raw)
+            .await
+            .unwrap();
+    }
+}
+
+fn eip1559(max_fee: u64) -> NewFeeRecord<'static> {
+    NewFeeRecord {
+        gas_limit: 100_000,
+        max_fee_per_gas: Some(max_fee),
+        max_priority_fee_per_gas: Some(2 * GWEI),
+        ..Default::default()
+    }
+}
+
+fn legacy(gas_price: u64) -> NewFeeRecord<'static> {
+    NewFeeRecord { gas_limit: 21_000, gas_price: Some(gas_price), ..Default::default() }
+}
+
+#[tokio::test]
+async fn test_fee_analytics_groups_by_day() {
+    let db = open_db().await;
+    let today = Utc::now().timestamp() / 86_400 * 86_400;
+    let day1 = today - 2 * 86_400;
+    let day2 = today - 86_400;
+
+    db.seed(1, eip1559(50 * GWEI), Some((21_000, 30 * GWEI)), day1 + 3_600).await;
+    db.seed(2, eip1559(40 * GWEI), Some((50_000, 20 * GWEI)), day1 + 7_200).await;
+    // 仍待确认：计入笔数，不计入费用
+    db.seed(3, legacy(10 * GWEI), None, day1 + 9_000).await;
+    db.seed(4, legacy(15 * GWEI), Some((21_000, 15 * GWEI)), day2 + 60).await;
+    // 窗口之外
+    db.seed(5, legacy(99 * GWEI), Some((21_000, 99 * GWEI)), today - 40 * 86_400).await;
+
+    for (price, at) in [(20 * GWEI, day1 + 100), (30 * GWEI, day1 + 200), (12 * GWEI, day2 + 100)] {
+        db.storage.record_gas_price_sample(NETWORK, price, Utc.timestamp_opt(at, 0).unwrap()).await.unwrap();
+    }
+
+    let since = Utc::now() - chrono::Duration::days(30);
+    let (buckets, totals) = db.storage.fee_analytics(NETWORK, since, FeeGrouping::Day).await.unwrap();
+    assert_eq!(buckets.len(), 2);
+
+    let first = &buckets[0];
+    assert_eq!(first.bucket_start, Utc.timestamp_opt(day1, 0).single());
+    assert_eq!((first.transactions, first.confirmed, first.total_gas_used), (3, 2, 71_000));
+    // 21000 * 30 gwei + 50000 * 20 gwei
+    assert_eq!(first.total_fee_wei, "1630000000000000");
+    assert_eq!(first.avg_fee_wei.as_deref(), Some("815000000000000"));
+    assert_eq!(first.avg_effective_gas_price, Some(25 * GWEI));
+    assert_eq!(first.p50_effective_gas_price, Some(20 * GWEI));
+    assert_eq!(first.p95_effective_gas_price, Some(30 * GWEI));
+    // (50 - 30) gwei * 21000 + (40 - 20) gwei * 50000
+    assert_eq!(first.overpayment_wei, "1420000000000000");
+    assert_eq!(first.market_avg_gas_price, Some(25 * GWEI));
+
+    let second = &buckets[1];
+    assert_eq!(second.bucket_start, Utc.timestamp_opt(day2, 0).single());
+    assert_eq!((second.transactions, second.confirmed), (1, 1));
+    assert_eq!(second.total_fee_wei, "315000000000000");
+    // legacy 发送没有 max fee，不计超付
+    assert_eq!(second.overpayment_wei, "0");
+    assert_eq!(second.market_avg_gas_price, Some(12 * GWEI));
+
+    assert_eq!(totals.bucket_start, None);
+    assert_eq!((totals.transactions, totals.confirmed, totals.total_gas_used), (4, 3, 92_000));
+    assert_eq!(totals.total_fee_wei, "1945000000000000");
+    assert_eq!(totals.avg_effective_gas_price, Some(21_666_666_666));
+    assert_eq!(totals.overpayment_wei, "1420000000000000");
+}
+
+/// 节点：收到即入池；`mine` 之后才有回执
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    receipts: Mutex<HashMap<H256, TransactionReceipt>>,
+}
+
+impl MockChain {
+    fn mine(&self, hash: H256, gas_used: u64, effective: u64, success: bool) {
+        let receipt = TransactionReceipt {
+            transaction_hash: hash,
+            gas_used: Some(U256::from(gas_used)),
+            effective_gas_price: Some(U256::from(effective)),
+            status: Some(U64::from(success as u64)),
+            block_number: Some(U64::from(BLOCK)),
+            ..Default::default()
+        };
+        self.receipts.lock().unwrap().insert(hash, receipt);
+    }
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3 * GWEI);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        Ok(self.receipts.lock().unwrap().get(&tx_hash).cloned())
+    }
+}
+
+fn transfer(value: u64) -> TypedTransaction {
+    TransactionRequest::new()
+        .to("0x000000000000000000000000000000000000dEaD".parse::<Address>().unwrap())
+        .value(value)
+        .into()
+}
+
+#[tokio::test]
+async fn test_confirmation_fills_in_gas_used_and_status() {
+    let db = open_db().await;
+    let storage = Arc::new(db.storage);
+    let chain = Arc::new(MockChain::default());
+    let log = SigningIntentLog::new(storage.clone(), chain.clone());
+    let signer: LocalWallet = KEY.parse().unwrap();
+
+    let ok = log.send("hot", NETWORK, &signer, transfer(1)).await.unwrap();
+    let reverted = log.send("hot", NETWORK, &signer, transfer(2)).await.unwrap();
+
+    let broadcast = storage.fee_record(NETWORK, &ok).await.unwrap().unwrap();
+    assert_eq!(broadcast.gas_limit, 21_000);
+    assert_eq!(broadcast.gas_price, Some((3 * GWEI) as i64));
+    assert_eq!(broadcast.speed.as_deref(), Some("node"));
+    assert_eq!(broadcast.gas_used, None);
+
+    let poller = ConfirmationPoller::new(FeeTrackingConfig::default(), storage.clone(), chain.clone());
+    // 尚未出块
+    assert_eq!(poller.run_once().await, ConfirmationReport { checked: 2, ..Default::default() });
+
+    chain.mine(ok.parse().unwrap(), 21_000, 2 * GWEI, true);
+    chain.mine(reverted.parse().unwrap(), 18_500, 2 * GWEI, false);
+    let report = poller.run_once().await;
+    assert_eq!(report, ConfirmationReport { checked: 2, confirmed: 1, reverted: 1, errors: 0 });
+
+    let confirmed = storage.fee_record(NETWORK, &ok).await.unwrap().unwrap();
+    assert_eq!(confirmed.gas_used, Some(21_000));
+    assert_eq!(confirmed.effective_gas_price, Some((2 * GWEI) as i64));
+    assert_eq!(confirmed.fee_paid_wei.as_deref(), Some("42000000000000"));
+    assert!(confirmed.confirmed_at.is_some());
+    assert_eq!(storage.fee_record(NETWORK, &reverted).await.unwrap().unwrap().gas_used, Some(18_500));
+
+    assert_eq!(storage.transaction_by_hash(&ok).await.unwrap().unwrap().status, "confirmed");
+    assert_eq!(storage.transaction_by_hash(&reverted).await.unwrap().unwrap().status, "failed");
+
+    // 已补全的行不再轮询
+    assert_eq!(poller.run_once().await, ConfirmationReport::default());
+}
+
+#[tokio::test]
+async fn test_fee_analytics_endpoint() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(1),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    let hash = tx_hash(7);
+    let fee = NewFeeRecord { network: NETWORK, tx_hash: &hash, ..eip1559(50 * GWEI) };
+    server.storage.record_fee_broadcast(&fee).await.unwrap();
+    server.storage.complete_fee_record(NETWORK, &hash, 21_000, 30 * GWEI).await.unwrap();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    app.get("/api/analytics/fees").add_query_param("network", NETWORK).await.assert_status_unauthorized();
+
+    let res = app
+        .get("/api/analytics/fees")
+        .add_header("Authorization", API_KEY)
+        .add_query_param("network", NETWORK)
+        .add_query_param("window", "2y")
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_WINDOW");
+
+    let res = app
+        .get("/api/analytics/fees")
+        .add_header("Authorization", API_KEY)
+        .add_query_param("network", NETWORK)
+        .add_query_param("window", "7d")
+        .add_query_param("group_by", "hour")
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["window_secs"], 7 * 86_400);
+    assert_eq!(body["group_by"], "hour");
+    assert_eq!(body["totals"]["transactions"], 1);
+    assert_eq!(body["totals"]["total_fee_wei"], "630000000000000");
+    assert_eq!(body["totals"]["overpayment_wei"], "420000000000000");
+    assert_eq!(body["buckets"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_confirmation_stores_actual_fee_on_transaction() {
+    let db = open_db().await;
+    let raw = db.raw.clone();
+    let storage = Arc::new(db.storage);
+    let chain = Arc::new(MockChain::default());
+    let log = SigningIntentLog::new(storage.clone(), chain.clone());
+    let signer: LocalWallet = KEY.parse().unwrap();
+
+    let ok = log.send("hot", NETWORK, &signer, transfer(1)).await.unwrap();
+    let reverted = log.send("hot", NETWORK, &signer, transfer(2)).await.unwrap();
+    let pending = storage.transaction_by_hash(&ok).await.unwrap().unwrap();
+    assert_eq!((pending.fee_actual.as_deref(), pending.block_number), (None, None));
+
+    chain.mine(ok.parse().unwrap(), 21_000, 2 * GWEI, true);
+    chain.mine(reverted.parse().unwrap(), 18_500, 2 * GWEI, false);
+    let poller = ConfirmationPoller::new(FeeTrackingConfig::default(), storage.clone(), chain.clone());
+    assert_eq!(poller.run_once().await.errors, 0);
+
+    // 21000 * 2 gwei；revert 的交易同样付了 gas
+    let mined = storage.transaction_by_hash(&ok).await.unwrap().unwrap();
+    assert_eq!(mined.fee_actual.as_deref(), Some("0.000042000000000000"));
+    assert_eq!(mined.block_number, Some(BLOCK as i64));
+    assert!(mined.confirmed_at.is_some());
+    let failed = storage.transaction_by_hash(&reverted).await.unwrap().unwrap();
+    assert_eq!(failed.fee_actual.as_deref(), Some("0.000037000000000000"));
+    assert_eq!(failed.block_number, Some(BLOCK as i64));
+    assert_eq!(failed.confirmed_at, None, "a reverted send is not confirmed");
+
+    // 重新计算过 integrity hash，历史查询照常通过校验
+    let history = storage.get_wallet_transactions(&mined.wallet_id).await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(history.iter().all(|tx| tx.block_number == Some(BLOCK as i64)));
+
+    // 未知哈希不更新任何行
+    assert_eq!(storage.finalize_transaction(&tx_hash(9), "0.1", Some(Utc::now()), 1).await.unwrap(), 0);
+
+    // 篡改回执字段后校验失败
+    sqlx::query("UPDATE transactions SET fee_actual = '0.000001' WHERE id = ?1")
+        .bind(&mined.id)
+        .execute(&raw)
+        .await
+        .unwrap();
+    assert!(storage.get_wallet_transactions(&mined.wallet_id).await.is_err());
+}
@@ -2,15 +2,15 @@ mod util;
 
 use axum::extract::State;
 use axum::http::StatusCode;
-use axum::Json;
-use axum::response::Response;
+use axum::response::{IntoResponse, Response};
 use http_body_util::BodyExt;
 use serde_json::Value;
 use std::sync::Arc;
 
 use defi_hot_wallet::api::handlers::{bridge_assets, health_check};
 use defi_hot_wallet::api::server::WalletServer;
-use defi_hot_wallet::api::types::BridgeAssetsRequest;
+use defi_hot_wallet::api::types::{BridgeAssets, BridgeAssetsRequest};
+use defi_hot_wallet::api::validators::{Validate, ValidJson};
 use defi_hot_wallet::core::config::{BlockchainConfig, SecurityConfig, StorageConfig, WalletConfig};
 
 // Helper function to extract status and body from Response
@@ -23,12 +23,24 @@ async fn extract_response(res: Response) -> (StatusCode, Value) {
 
 #[tokio::test(flavor = "current_thread")]
 async fn handlers_health_and_metrics() {
+    util::set_test_env();
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        WalletConfig { storage: StorageConfig { database_url: "sqlite::memory:".to_string(), max_connections: Some(1), connection_timeout_seconds: Some(30), read_database_url: None, }, ..Default::default() },
+        None,
+        None,
+    )
+    .await
+    .expect("wallet server init");
+
     // health_check()
-    let h = health_check().await;
+    let h = health_check(State(Arc::new(server))).await;
     let body: Value = h.0;
     assert_eq!(body["status"], "healthy");
     assert!(body["version"].is_string());
     assert!(body["timestamp"].is_string());
+    assert_eq!(body["bridge_backend"], "mock");
 
     // metrics_handler() - 注：metrics_handler 尚未实现
     // let m = metrics_handler().await;
@@ -51,6 +63,7 @@ async fn handlers_bridge_assets_branches() {
             database_url: "sqlite::memory:".to_string(),
             max_connections: Some(5),
             connection_timeout_seconds: Some(30),
+            read_database_url: None,
         },
         blockchain: BlockchainConfig {
             networks: std::collections::HashMap::new(),
@@ -59,13 +72,34 @@ async fn handlers_bridge_assets_branches() {
         multi_sig_threshold: 1,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
     let server = WalletServer::new("127.0.0.1".to_string(), 8080, config, None)
         .await
         .expect("wallet server init");
     let state = State(Arc::new(server));
 
-    // empty parameters -> Invalid parameters (rate limiting happens after basic validation)
+    // empty parameters / invalid amount are rejected by the ValidJson extractor
     let req = BridgeAssetsRequest {
         from_wallet: "".to_string(),
         from_chain: "eth".to_string(),
@@ -74,13 +108,8 @@ async fn handlers_bridge_assets_branches() {
         amount: "1.0".to_string(),
         client_request_id: None,
     };
-    let headers = axum::http::HeaderMap::new();
-    let res = bridge_assets(state.clone(), headers.clone(), Json(req)).await;
-    let (code, body) = extract_response(res).await;
-    assert_eq!(code, StatusCode::BAD_REQUEST);
-    assert_eq!(body["error"], "Invalid parameters");
+    assert_eq!(BridgeAssets::validate(req).unwrap_err().code(), "INVALID_WALLET_NAME");
 
-    // invalid amount (non-numeric)
     let req2 = BridgeAssetsRequest {
         from_wallet: "w".to_string(),
         from_chain: "eth".to_string(),
@@ -89,24 +118,23 @@ async fn handlers_bridge_assets_branches() {
         amount: "abc".to_string(),
         client_request_id: None,
     };
-    let res2 = bridge_assets(state.clone(), headers.clone(), Json(req2)).await;
-    let (code2, body2) = extract_response(res2).await;
-    assert_eq!(code2, StatusCode::BAD_REQUEST);
-    assert_eq!(body2["error"], "Invalid amount");
+    assert_eq!(BridgeAssets::validate(req2).unwrap_err().code(), "INVALID_AMOUNT");
 
-    // unsupported chain
+    // known network that is not configured for bridging
     let req3 = BridgeAssetsRequest {
         from_wallet: "w".to_string(),
-        from_chain: "invalid_chain".to_string(),
+        from_chain: "btc".to_string(),
         to_chain: "polygon".to_string(),
         token: "USDC".to_string(),
         amount: "1.0".to_string(),
         client_request_id: None,
     };
-    let res3 = bridge_assets(state.clone(), headers.clone(), Json(req3)).await;
+    let headers = axum::http::HeaderMap::new();
+    let req3 = BridgeAssets::validate(req3).expect("valid request");
+    let res3 = bridge_assets(state.clone(), headers.clone(), ValidJson(req3)).await.into_response();
     let (code3, body3) = extract_response(res3).await;
     assert_eq!(code3, StatusCode::BAD_REQUEST);
-    assert_eq!(body3["error"], "Unsupported chain");
+    assert_eq!(body3["error"]["message"], "Unsupported chain");
 
     // success path: create wallet first then call with fresh server (avoid rate limiting)
     std::env::set_var("BRIDGE_MOCK_FORCE_SUCCESS", "1");
@@ -115,6 +143,7 @@ async fn handlers_bridge_assets_branches() {
             database_url: "sqlite::memory:".to_string(),
             max_connections: Some(5),
             connection_timeout_seconds: Some(30),
+            read_database_url: None,
         },
         blockchain: BlockchainConfig {
             networks: std::collections::HashMap::new(),
@@ -123,13 +152,34 @@ async fn handlers_bridge_assets_branches() {
         multi_sig_threshold: 1,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
     let server2 = WalletServer::new("127.0.0.1".to_string(), 8080, config2, None)
         .await
         .expect("wallet server init");
     let state2 = State(Arc::new(server2));
     let wm_arc = state2.0.clone();
-    wm_arc.wallet_manager.create_wallet("test_w", false).await.expect("create wallet");
+    wm_arc.wallet_manager.create_wallet("test_w", "test_password", false).await.expect("create wallet");
 
     let req4 = BridgeAssetsRequest {
         from_wallet: "test_w".to_string(),
@@ -141,7 +191,8 @@ async fn handlers_bridge_assets_branches() {
     };
 
     let headers2 = axum::http::HeaderMap::new();
-    let res4 = bridge_assets(state2, headers2, Json(req4)).await;
+    let req4 = BridgeAssets::validate(req4).expect("valid request");
+    let res4 = bridge_assets(state2, headers2, ValidJson(req4)).await.into_response();
     let (status4, body4) = extract_response(res4).await;
     
     // Bridge may succeed or fail depending on wallet state
@@ -159,7 +210,8 @@ async fn handlers_bridge_assets_branches() {
             status4.is_client_error() || status4.is_server_error(),
             "Expected error status code, got: {:?}", status4
         );
-        assert!(body4["error"].is_string() && !body4["error"].as_str().unwrap_or("").is_empty(), 
+        let message = &body4["error"]["message"];
+        assert!(message.is_string() && !message.as_str().unwrap_or("").is_empty(), 
             "Error message should not be empty");
     }
 }
@@ -0,0 +1,166 @@
+//! ETag 与响应缓存：If-None-Match 命中返回 304、创建wallet后列表换新 ETag、
+//! 不同user之间不共享缓存的响应体，以及不在白名单内的敏感路由（备份）不被缓存
+
+mod util;
+
+use axum_test::{TestResponse, TestServer};
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::http_cache::{self, CACHEABLE_ROUTES};
+
+const API_KEY: &str = "http-cache-admin-key-0123456789ab";
+const ALICE: &str = "http-cache-alice-token";
+const BOB: &str = "http-cache-bob-token";
+const ADDRESS: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+
+struct Harness {
+    app: TestServer,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    std::env::set_var("TEST_SKIP_DECRYPT", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY)).await;
+
+    for (token, email, wallets) in [
+        (ALICE, "cache-alice@example.com", &["alice_main", "alice_savings"][..]),
+        (BOB, "cache-bob@example.com", &["bob_main"][..]),
+    ] {
+        let user_id = util::sign_in(&server, email, token).await;
+        for name in wallets {
+            server.wallet_manager.create_wallet(name, "C4che!Wallet#2024", false).await.unwrap();
+            server.storage.store_wallet(name, b"sealed", false).await.unwrap();
+            server.user_db.link_wallet(&user_id, name, ADDRESS, None).await.unwrap();
+        }
+    }
+
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, _dir: dir }
+}
+
+impl Harness {
+    async fn list(&self, token: &str, if_none_match: Option<&str>) -> TestResponse {
+        let mut req = self.app.get("/api/wallets").add_header("Authorization", format!("Bearer {}", token));
+        if let Some(etag) = if_none_match {
+            req = req.add_header("If-None-Match", etag.to_string());
+        }
+        req.await
+    }
+}
+
+fn header(res: &TestResponse, name: &str) -> Option<String> {
+    res.headers().get(name).map(|v| v.to_str().unwrap().to_string())
+}
+
+fn names(res: &TestResponse) -> Vec<String> {
+    res.json::<Vec<Value>>().iter().map(|w| w["name"].as_str().unwrap().to_string()).collect()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_matching_etag_returns_not_modified() {
+    let h = build().await;
+    let first = h.list(ALICE, None).await;
+    first.assert_status_ok();
+    let etag = header(&first, "etag").unwrap();
+    assert_eq!(header(&first, "cache-control").as_deref(), Some("private, max-age=5"));
+
+    let res = h.list(ALICE, Some(&etag)).await;
+    res.assert_status(axum::http::StatusCode::NOT_MODIFIED);
+    assert!(res.as_bytes().is_empty());
+    assert_eq!(header(&res, "etag"), Some(etag.clone()));
+    // 列表中的任一标签命中即可，弱比较
+    let res = h.list(ALICE, Some(&format!("\"stale\", W/{}", etag))).await;
+    res.assert_status(axum::http::StatusCode::NOT_MODIFIED);
+
+    // 缓存命中的 200 与首次构建的响应一致
+    let again = h.list(ALICE, Some("\"stale\"")).await;
+    again.assert_status_ok();
+    assert_eq!(header(&again, "etag"), Some(etag));
+    assert_eq!(again.text(), first.text());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_writes_invalidate_the_cached_list() {
+    let h = build().await;
+    let etag = header(&h.list(ALICE, None).await, "etag").unwrap();
+
+    h.app
+        .post("/api/wallets")
+        .add_header("Authorization", format!("Bearer {}", ALICE))
+        .json(&json!({ "name": "alice_new", "wallet_address": ADDRESS }))
+        .await
+        .assert_status_ok();
+    let res = h.list(ALICE, Some(&etag)).await;
+    res.assert_status_ok();
+    let created_etag = header(&res, "etag").unwrap();
+    assert_ne!(created_etag, etag);
+    assert!(names(&res).contains(&"alice_new".to_string()));
+
+    // 描述/元数据写入推进 wallets epoch
+    h.app
+        .patch("/api/wallets/alice_main")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "description": "daily spending" }))
+        .await
+        .assert_status_ok();
+    let res = h.list(ALICE, Some(&created_etag)).await;
+    res.assert_status_ok();
+    let listed = res.json::<Vec<Value>>();
+    assert_eq!(listed.iter().find(|w| w["name"] == "alice_main").unwrap()["description"], "daily spending");
+
+    // 删除同样换新 ETag
+    let etag = header(&res, "etag").unwrap();
+    h.app
+        .delete("/api/wallets/alice_new")
+        .add_header("Authorization", format!("Bearer {}", ALICE))
+        .await
+        .assert_status_ok();
+    let res = h.list(ALICE, Some(&etag)).await;
+    res.assert_status_ok();
+    assert!(!names(&res).contains(&"alice_new".to_string()));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_principals_never_share_cached_bodies() {
+    let h = build().await;
+    let alice = h.list(ALICE, None).await;
+    let bob = h.list(BOB, None).await;
+    assert_eq!(names(&alice), ["alice_main", "alice_savings"]);
+    assert_eq!(names(&bob), ["bob_main"]);
+    let (alice_etag, bob_etag) = (header(&alice, "etag").unwrap(), header(&bob, "etag").unwrap());
+    assert_ne!(alice_etag, bob_etag);
+
+    // 别人的 ETag 不会让自己拿到 304，缓存命中也只返回自己的列表
+    let res = h.list(BOB, Some(&alice_etag)).await;
+    res.assert_status_ok();
+    assert_eq!(names(&res), ["bob_main"]);
+    assert_eq!(header(&res, "etag"), Some(bob_etag));
+    assert_eq!(names(&h.list(ALICE, None).await), ["alice_main", "alice_savings"]);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_sensitive_routes_are_never_cached() {
+    for route in ["GET /api/wallets/:name/backup", "POST /api/wallets/:name/export_keystore"] {
+        assert!(http_cache::policy(route).is_none(), "{}", route);
+    }
+    assert!(CACHEABLE_ROUTES.iter().all(|p| !p.route.contains("backup") && !p.route.contains("export")));
+
+    let h = build().await;
+    for _ in 0..2 {
+        let res = h
+            .app
+            .get("/api/wallets/alice_main/backup")
+            .add_header("X-API-KEY", API_KEY)
+            .add_header("If-None-Match", "*")
+            .await;
+        res.assert_status_ok();
+        assert!(header(&res, "etag").is_none());
+        assert_eq!(header(&res, "cache-control").as_deref(), Some("no-store"));
+    }
+}
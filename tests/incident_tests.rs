@@ -0,0 +1,247 @@
+//! 进程事故与错误分类：故意 panic 的任务留下事故记录与指标、数据库写入失败时
+//! 记录器不退出、各类错误来源的分类映射，以及 `/api/admin/incidents` 的汇总
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{JobsConfig, StorageConfig, WalletConfig};
+use defi_hot_wallet::core::error_class::{classify, classify_or, classify_wallet_error, ErrorClass};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::monitoring::WalletMetrics;
+use defi_hot_wallet::ops::incidents;
+use defi_hot_wallet::ops::jobs::{Job, JobRunner, Schedule};
+use defi_hot_wallet::ops::maintenance::MaintenanceMode;
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{IncidentRecord, NewIncident, WalletStorage};
+
+const API_KEY: &str = "incidents-test-key-0123456789abcdef";
+
+/// 每次运行都 panic
+struct ExplodingJob;
+
+#[async_trait]
+impl Job for ExplodingJob {
+    fn name(&self) -> &str {
+        "exploder"
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Every { every: Duration::from_secs(3600), immediate: false }
+    }
+
+    async fn run(&self, _cancel: CancellationToken) -> anyhow::Result<()> {
+        panic!("exploder blew up");
+    }
+}
+
+fn runner() -> JobRunner {
+    let runner = JobRunner::new(JobsConfig::default(), Arc::new(MaintenanceMode::new()));
+    runner.register(Arc::new(ExplodingJob));
+    runner
+}
+
+/// 等待后台 writer 落库
+async fn wait_for_incidents(storage: &WalletStorage, expected: usize) -> Vec<IncidentRecord> {
+    let since = chrono::Utc::now() - chrono::Duration::hours(1);
+    for _ in 0..100 {
+        let (incidents, _) = storage.recent_incidents(since, 10).await.unwrap();
+        if incidents.len() >= expected {
+            return incidents;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("expected {} incidents", expected);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_panicking_job_records_incident_and_metric() {
+    incidents::install_panic_hook();
+    let storage = Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap());
+    let metrics = Arc::new(WalletMetrics::new().unwrap());
+    let writer = incidents::attach(storage.clone(), metrics.clone());
+
+    let state = runner().run_now("exploder").await.unwrap();
+    assert_eq!(state.panics, 1);
+    assert_eq!(state.last_error.as_deref(), Some("panicked: exploder blew up"));
+    // hook 同步计数，不等落库
+    assert_eq!(metrics.process_panics.with_label_values(&["job:exploder"]).get(), 1);
+
+    let incidents = wait_for_incidents(&storage, 1).await;
+    let incident = &incidents[0];
+    assert_eq!(incident.task, "job:exploder");
+    assert_eq!(incident.message, "exploder blew up");
+    assert!(incident.location.as_deref().is_some_and(|l| l.contains("incident_tests.rs")));
+    assert_eq!(incident.backtrace_hash.len(), 16);
+    assert!(metrics.export_metrics().unwrap().contains("process_panics_total{task=\"job:exploder\"} 1"));
+
+    // 同一位置再次 panic：同一个 backtrace hash
+    runner().run_now("exploder").await.unwrap();
+    let incidents = wait_for_incidents(&storage, 2).await;
+    assert_eq!(incidents[0].backtrace_hash, incidents[1].backtrace_hash);
+
+    incidents::detach();
+    tokio::time::timeout(Duration::from_secs(1), writer).await.unwrap().unwrap();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_writer_survives_a_failed_insert() {
+    incidents::install_panic_hook();
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display());
+    let storage = Arc::new(WalletStorage::new_with_url(&url).await.unwrap());
+    let metrics = Arc::new(WalletMetrics::new().unwrap());
+    let writer = incidents::attach(storage.clone(), metrics.clone());
+
+    // 表被移走后插入必然失败；报告改写到 stderr，writer 继续运行
+    let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+    sqlx::query("ALTER TABLE process_incidents RENAME TO process_incidents_moved").execute(&pool).await.unwrap();
+    runner().run_now("exploder").await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!writer.is_finished());
+    assert_eq!(metrics.process_panics.with_label_values(&["job:exploder"]).get(), 1);
+
+    // 表恢复后照常落库
+    sqlx::query("ALTER TABLE process_incidents_moved RENAME TO process_incidents").execute(&pool).await.unwrap();
+    runner().run_now("exploder").await.unwrap();
+    assert_eq!(wait_for_incidents(&storage, 1).await.len(), 1);
+
+    incidents::detach();
+    tokio::time::timeout(Duration::from_secs(1), writer).await.unwrap().unwrap();
+}
+
+#[test]
+fn test_wallet_errors_map_to_stable_classes() {
+    let cases = [
+        (WalletError::ValidationError("x".into()), ErrorClass::Validation),
+        (WalletError::InvalidAddress("x".into()), ErrorClass::Validation),
+        (WalletError::SecurityError("x".into()), ErrorClass::Unauthorized),
+        (WalletError::NotFoundError("x".into()), ErrorClass::NotFound),
+        (WalletError::NetworkBusy("x".into()), ErrorClass::RateLimited),
+        (WalletError::InsufficientFunds("x".into()), ErrorClass::InsufficientFunds),
+        (WalletError::BlockchainError("x".into()), ErrorClass::ChainRpc),
+        (WalletError::TimeoutError("x".into()), ErrorClass::Timeout),
+        (WalletError::StorageError("x".into()), ErrorClass::Database),
+        (WalletError::DeserializationError("x".into()), ErrorClass::Serialization),
+        (WalletError::DecryptionError("x".into()), ErrorClass::Crypto),
+        (WalletError::ConfigError("x".into()), ErrorClass::Config),
+        (WalletError::Other("x".into()), ErrorClass::Internal),
+    ];
+    for (error, class) in cases {
+        assert_eq!(classify_wallet_error(&error), class, "{}", error);
+        assert_eq!(classify(&anyhow::Error::new(error)), class);
+    }
+}
+
+#[tokio::test]
+async fn test_anyhow_sources_map_to_stable_classes() {
+    assert_eq!(classify(&sqlx::Error::RowNotFound.into()), ErrorClass::NotFound);
+    assert_eq!(classify(&sqlx::Error::PoolTimedOut.into()), ErrorClass::PoolExhausted);
+    assert_eq!(classify(&sqlx::Error::PoolClosed.into()), ErrorClass::Database);
+
+    let pool = sqlx::sqlite::SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+    sqlx::query("CREATE TABLE t (id INTEGER PRIMARY KEY)").execute(&pool).await.unwrap();
+    sqlx::query("INSERT INTO t (id) VALUES (1)").execute(&pool).await.unwrap();
+    let duplicate = sqlx::query("INSERT INTO t (id) VALUES (1)").execute(&pool).await.unwrap_err();
+    assert_eq!(classify(&duplicate.into()), ErrorClass::Conflict);
+
+    let provider = ethers::providers::ProviderError::CustomError("execution reverted".into());
+    assert_eq!(classify(&provider.into()), ErrorClass::ChainRpc);
+
+    let http = reqwest::Client::new().get("not a url").send().await.unwrap_err();
+    assert_eq!(classify(&http.into()), ErrorClass::ExternalHttp);
+
+    let json = serde_json::from_str::<Value>("{").unwrap_err();
+    assert_eq!(classify(&json.into()), ErrorClass::Serialization);
+
+    let elapsed = tokio::time::timeout(Duration::ZERO, std::future::pending::<()>()).await.unwrap_err();
+    assert_eq!(classify(&elapsed.into()), ErrorClass::Timeout);
+
+    let io = std::io::Error::new(std::io::ErrorKind::TimedOut, "read timed out");
+    assert_eq!(classify(&io.into()), ErrorClass::Timeout);
+    let io = std::io::Error::other("disk");
+    assert_eq!(classify(&io.into()), ErrorClass::Io);
+
+    // 上下文包装不影响分类；纯文本错误取调用方给的默认值
+    let wrapped = anyhow::Error::from(sqlx::Error::RowNotFound).context("loading wallet");
+    assert_eq!(classify(&wrapped), ErrorClass::NotFound);
+    assert_eq!(classify(&anyhow::anyhow!("Failed to load: boom")), ErrorClass::Internal);
+    assert_eq!(classify_or(&anyhow::anyhow!("Failed to load: boom"), ErrorClass::Database), ErrorClass::Database);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_incidents_endpoint_aggregates_window() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    let storage = server.storage.clone();
+    let metrics = server.metrics.clone();
+    let now = chrono::Utc::now().timestamp();
+    for (task, age) in [("job:backups", 60), ("tx_watch", 600), ("job:backups", 3 * 3600)] {
+        storage
+            .record_incident(&NewIncident {
+                task,
+                message: "boom",
+                location: Some("src/ops/db_backup.rs:10:5"),
+                backtrace_hash: "0123456789abcdef",
+                thread: Some("tokio-runtime-worker"),
+                occurred_at: now - age,
+            })
+            .await
+            .unwrap();
+    }
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    // 两次未认证、一次参数错误
+    app.get("/api/admin/incidents").await.assert_status_unauthorized();
+    app.get("/api/admin/jobs").await.assert_status_unauthorized();
+    app.get("/api/admin/incidents?limit=abc").add_header("X-API-KEY", API_KEY).await.assert_status_bad_request();
+
+    let res = app.get("/api/admin/incidents?window_secs=3600&limit=1").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["window_secs"], 3600);
+    assert_eq!(body["panic_count"], 2, "the 3h old panic is outside the window");
+    assert_eq!(body["panics"].as_array().unwrap().len(), 1);
+    assert_eq!(body["panics"][0]["task"], "job:backups");
+    assert_eq!(body["panics"][0]["occurred_at"], now - 60);
+    assert_eq!(
+        body["error_classes"],
+        serde_json::json!([{ "class": "unauthorized", "count": 2 }, { "class": "validation", "count": 1 }])
+    );
+
+    // 窗口上限一天
+    let body: Value =
+        app.get("/api/admin/incidents?window_secs=999999").add_header("X-API-KEY", API_KEY).await.json();
+    assert_eq!(body["window_secs"], 86400);
+    assert_eq!(body["panic_count"], 3);
+
+    let exported = metrics.export_metrics().unwrap();
+    assert!(exported.contains("http_requests_total{error_class=\"unauthorized\",status_class=\"4xx\"} 2"));
+    assert!(exported.contains("http_requests_total{error_class=\"none\",status_class=\"2xx\"} 2"));
+}
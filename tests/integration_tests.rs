@@ -11,6 +11,7 @@ fn create_test_config() -> WalletConfig {
             database_url: "sqlite::memory:".to_string(),
             max_connections: Some(1),
             connection_timeout_seconds: Some(5),
+            read_database_url: None,
         },
         blockchain: BlockchainConfig {
             networks: HashMap::new(),
@@ -19,6 +20,27 @@ fn create_test_config() -> WalletConfig {
         multi_sig_threshold: 2,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     }
 }
 
@@ -33,7 +55,7 @@ async fn test_wallet_creation_and_bridge_integration() {
     let wallet_manager = WalletManager::new(&config).await.unwrap();
 
     // Test wallet creation
-    wallet_manager.create_wallet("test_wallet", false).await.unwrap();
+    wallet_manager.create_wallet("test_wallet", "test_password", false).await.unwrap();
 
     // Test listing wallets
     let wallets = wallet_manager.list_wallets().await.unwrap();
@@ -57,7 +79,7 @@ async fn test_balance_and_transaction_integration() {
     let wallet_manager = WalletManager::new(&config).await.unwrap();
 
     // Create wallet
-    let _wallet_info = wallet_manager.create_wallet("balance_test", false).await.unwrap();
+    wallet_manager.create_wallet("balance_test", "test_password", false).await.unwrap();
 
     // Test balance (may fail without real network)
     // let balance = wallet_manager.get_balance("balance_test", "eth").await;
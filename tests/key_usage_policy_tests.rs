@@ -0,0 +1,191 @@
+//! sign密钥使用量统计与轮换策略集成测试
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{KeyPolicyMode, KeyRotationPolicy, SecurityConfig, StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::monitoring::SecurityEventType;
+use defi_hot_wallet::security::key_usage::{signing_key_label, USAGE_FLUSH_INTERVAL};
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "key-usage-policy-test-key-0123456789ab";
+
+async fn build_server(policy: KeyRotationPolicy) -> WalletServer {
+    build_server_with(SecurityConfig { key_rotation: policy, ..Default::default() }).await
+}
+
+async fn build_server_with(security: SecurityConfig) -> WalletServer {
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        security,
+        ..Default::default()
+    };
+    WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap()
+}
+
+fn budget(max: u64, mode: KeyPolicyMode) -> KeyRotationPolicy {
+    KeyRotationPolicy { max_signatures_per_key: Some(max), max_key_age_secs: None, policy_mode: mode }
+}
+
+#[tokio::test]
+async fn test_usage_increments_are_persisted_by_batched_flush() {
+    let server = build_server(KeyRotationPolicy::default()).await;
+    for _ in 0..7 {
+        server.key_usage.record_signature("usage_wallet").await.unwrap();
+    }
+
+    // 不显式 flush：等待后台定时批量写入
+    let label = signing_key_label("usage_wallet");
+    let mut persisted = 0;
+    for _ in 0..10 {
+        tokio::time::sleep(USAGE_FLUSH_INTERVAL).await;
+        persisted = server.storage.rotation_get_version(&label, 1).await.unwrap().unwrap().usage_count;
+        if persisted == 7 {
+            break;
+        }
+    }
+    assert_eq!(persisted, 7);
+}
+
+#[tokio::test]
+async fn test_warn_mode_emits_event_at_threshold() {
+    let server = build_server(budget(3, KeyPolicyMode::Warn)).await;
+    for _ in 0..5 {
+        server.key_usage.record_signature("warn_wallet").await.unwrap();
+    }
+
+    let events = server.key_usage.security_monitor().get_recent_security_events(10).await;
+    let overdue: Vec<_> = events
+        .iter()
+        .filter(|e| matches!(e.event_type, SecurityEventType::KeyRotationOverdue))
+        .collect();
+    assert_eq!(overdue.len(), 1, "one event when the threshold is crossed");
+    assert_eq!(overdue[0].wallet_id.as_deref(), Some("warn_wallet"));
+    // 超出预算的两次sign都计入指标
+    assert_eq!(server.key_usage.metrics().key_rotation_overdue.get() as u64, 2);
+
+    let report = server.key_usage.usage("warn_wallet").await.unwrap();
+    assert_eq!(report.usage_count, 5);
+    assert_eq!(report.remaining_signatures, Some(0));
+    assert!(report.rotation_required);
+}
+
+#[tokio::test]
+async fn test_block_mode_rejects_and_rotation_restores_signing() {
+    let server = build_server(budget(3, KeyPolicyMode::Block)).await;
+    server
+        .wallet_manager
+        .create_wallet("block_wallet", "Block-Wallet-Secret1", false)
+        .await
+        .unwrap();
+
+    for _ in 0..3 {
+        server.key_usage.record_signature("block_wallet").await.unwrap();
+    }
+    let fourth = server.key_usage.record_signature("block_wallet").await;
+    assert!(matches!(fourth, Err(WalletError::KeyRotationRequired(_))));
+
+    let tracker = server.key_usage.clone();
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    // sign路径在触达链之前即被拒绝
+    let res = app
+        .post("/api/transactions/send")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({
+            "wallet_name": "block_wallet",
+            "to": "0x742d35Cc6634C0532925a3b844Bc454e4438f44e",
+            "amount": "0.1",
+            "network": "eth",
+            "password": "Block-Wallet-Secret1"
+        }))
+        .await;
+    res.assert_status_forbidden();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "KEY_ROTATION_REQUIRED");
+
+    let usage: Value = app
+        .get("/api/wallets/block_wallet/key_usage")
+        .add_header("X-API-KEY", API_KEY)
+        .await
+        .json();
+    assert_eq!(usage["version"], 1);
+    assert_eq!(usage["usage_count"], 3);
+    assert_eq!(usage["rotation_required"], true);
+    assert_eq!(usage["policy_mode"], "block");
+
+    let res = app
+        .post("/api/wallets/block_wallet/rotate-signing-key")
+        .add_header("X-API-KEY", API_KEY)
+        .await;
+    res.assert_status_ok();
+    let rotated: Value = res.json();
+    assert_eq!(rotated["old_version"], 1);
+    assert_eq!(rotated["new_version"], 2);
+
+    tracker.record_signature("block_wallet").await.unwrap();
+    let usage: Value = app
+        .get("/api/wallets/block_wallet/key_usage")
+        .add_header("X-API-KEY", API_KEY)
+        .await
+        .json();
+    assert_eq!(usage["version"], 2);
+    assert_eq!(usage["usage_count"], 1);
+    assert_eq!(usage["remaining_signatures"], 2);
+    assert_eq!(usage["rotation_required"], false);
+
+    // 旧版本已退役且使用量已落盘
+    tracker.flush().await;
+    let label = signing_key_label("block_wallet");
+    let v1 = storage.rotation_get_version(&label, 1).await.unwrap().unwrap();
+    assert!(v1.retired);
+    assert_eq!(v1.usage_count, 3);
+    let v2 = storage.rotation_get_version(&label, 2).await.unwrap().unwrap();
+    assert_eq!(v2.usage_count, 1);
+}
+
+#[tokio::test]
+async fn test_policy_from_config_file_is_enforced() {
+    // 与 hot_wallet 启动时相同：整个 [security] 段反序列化，未知的旧键被忽略
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        r#"
+[security]
+quantum_safe_default = true
+session_timeout_minutes = 30
+
+[security.key_rotation]
+max_signatures_per_key = 2
+policy_mode = "block"
+"#,
+    )
+    .unwrap();
+    let doc: toml::Value = toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let security: SecurityConfig = doc["security"].clone().try_into().unwrap();
+    assert_eq!(security.key_rotation, budget(2, KeyPolicyMode::Block));
+
+    let server = build_server_with(security).await;
+    for _ in 0..2 {
+        server.key_usage.record_signature("file_policy_wallet").await.unwrap();
+    }
+    let third = server.key_usage.record_signature("file_policy_wallet").await;
+    assert!(matches!(third, Err(WalletError::KeyRotationRequired(_))));
+}
@@ -0,0 +1,304 @@
+//! 带签名的 keystore 信封：合作方样例验签与导入、往返、单字节篡改、签名方不符、
+//! 缺少 meta 字段各自报不同的错误，以及 HTTP 接口把 meta 记进wallet元数据
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use ethers::core::k256::ecdsa::SigningKey;
+use rand::rngs::OsRng;
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::core::WalletManager;
+use defi_hot_wallet::crypto::keystore_envelope::{
+    parse_sender_key, sender_key, signing_payload, EnvelopeError, EnvelopeSignature, KeystoreEnvelope,
+};
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "keystore-envelope-api-key-0123456789ab";
+// 合作方导出的样例：包着 Web3 Secret Storage 的 scrypt 测试向量，键序与缩进保持对方原样
+const PARTNER_ENVELOPE: &str = include_str!("fixtures/keystore/enveloped_partner.json");
+// 上面样例去掉 signature 后的规范化形式，即对方实际签名的字节
+const PARTNER_CANONICAL: &str = include_str!("fixtures/keystore/enveloped_partner.canonical.json");
+const PARTNER_SENDER: &str = "03c06f64e771ba74e8bb1d3805d531d4b633db2e0d5ffe0616041e213c51d5cf57";
+const FIXTURE_PASSWORD: &str = "testpassword";
+const FIXTURE_ADDRESS: &str = "0x008aeeda4d805471df9b2a5b0f38a0c3bcba786b";
+const WALLET_PASSWORD: &str = "Envel0pedWallet";
+const EXPORT_PASSWORD: &str = "Exp0rtEnvelope";
+
+fn config() -> WalletConfig {
+    let mut config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    // 测试中降低导出成本
+    config.security.keystore_scrypt_n = 1024;
+    config.security.pbkdf2_iterations = 1_000;
+    config
+}
+
+async fn manager() -> WalletManager {
+    WalletManager::new(&config()).await.unwrap()
+}
+
+/// 导出一个新建 HD wallet 的信封，返回 (信封 JSON, 传输私钥)
+async fn exported(wm: &WalletManager) -> (Value, SigningKey) {
+    let transport = SigningKey::random(&mut OsRng);
+    wm.create_wallet("source", WALLET_PASSWORD, false).await.unwrap();
+    let envelope = wm
+        .export_enveloped_keystore("source", WALLET_PASSWORD, EXPORT_PASSWORD, Some("ops hot #2"), &transport)
+        .await
+        .unwrap();
+    (serde_json::from_str(&envelope.to_json().unwrap()).unwrap(), transport)
+}
+
+fn open(envelope: &Value, sender: &str) -> Result<(), EnvelopeError> {
+    KeystoreEnvelope::open(&envelope.to_string(), sender).map(|_| ())
+}
+
+#[test]
+fn test_partner_fixture_canonical_form() {
+    let document: Value = serde_json::from_str(PARTNER_ENVELOPE).unwrap();
+    // 排序后的键、无空白、非 ASCII 原样输出
+    assert_eq!(signing_payload(&document), PARTNER_CANONICAL);
+    assert!(!PARTNER_CANONICAL.contains('\n') && !PARTNER_CANONICAL.contains("\": "));
+    assert!(PARTNER_CANONICAL.starts_with(r#"{"keystore":{"Crypto":{"cipher":"aes-128-ctr""#));
+    assert!(PARTNER_CANONICAL.contains(r#""label":"Treasury cold 7 — Zürich""#));
+
+    // 重排成紧凑格式后签名依旧有效
+    let compact = serde_json::to_string(&document).unwrap();
+    assert!(KeystoreEnvelope::open(&compact, PARTNER_SENDER).is_ok());
+}
+
+#[tokio::test]
+async fn test_import_partner_fixture() {
+    let wm = manager().await;
+    let imported = wm
+        .import_enveloped_keystore("partner", PARTNER_ENVELOPE, FIXTURE_PASSWORD, WALLET_PASSWORD, PARTNER_SENDER)
+        .await
+        .unwrap();
+    assert_eq!(imported.address, FIXTURE_ADDRESS);
+    assert_eq!(imported.sender, PARTNER_SENDER);
+    assert_eq!(imported.meta.tool, "partner-vault/2.3.1");
+    assert_eq!(imported.meta.origin, "bip32-master");
+    assert_eq!(imported.meta.address, "0x008AeEda4D805471dF9b2A5B0f38A0C3bCBA786b");
+    assert_eq!(imported.meta.label.as_deref(), Some("Treasury cold 7 — Zürich"));
+    assert_eq!(wm.get_ethereum_address_from_master_key("partner", WALLET_PASSWORD).await.unwrap(), FIXTURE_ADDRESS);
+
+    // 非压缩形式的同一公钥也认
+    let uncompressed = hex::encode(parse_sender_key(PARTNER_SENDER).unwrap().to_encoded_point(false).as_bytes());
+    assert!(KeystoreEnvelope::open(PARTNER_ENVELOPE, &format!("0x{}", uncompressed)).is_ok());
+}
+
+#[tokio::test]
+async fn test_round_trip_with_generated_transport_key() {
+    let wm = manager().await;
+    let (envelope, transport) = exported(&wm).await;
+    let source_address = wm.get_ethereum_address_from_master_key("source", WALLET_PASSWORD).await.unwrap();
+
+    assert_eq!(envelope["version"], 1);
+    assert_eq!(envelope["signature"]["scheme"], "secp256k1-sha256");
+    assert_eq!(envelope["signature"]["sender"], sender_key(transport.verifying_key()));
+    assert_eq!(envelope["meta"]["origin"], "bip32-master");
+    assert_eq!(envelope["meta"]["label"], "ops hot #2");
+    assert_eq!(envelope["meta"]["tool"], format!("defi-hot-wallet/{}", env!("CARGO_PKG_VERSION")));
+    assert_eq!(envelope["meta"]["address"].as_str().unwrap().to_lowercase(), source_address.to_lowercase());
+    assert_eq!(envelope["keystore"]["crypto"]["kdfparams"]["n"], 1024);
+
+    let other = manager().await;
+    let imported = other
+        .import_enveloped_keystore(
+            "copy",
+            &envelope.to_string(),
+            EXPORT_PASSWORD,
+            WALLET_PASSWORD,
+            &sender_key(transport.verifying_key()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(imported.address.to_lowercase(), source_address.to_lowercase());
+
+    // 再导出：导入的私钥来源标记为 imported
+    let again = other
+        .export_enveloped_keystore("copy", WALLET_PASSWORD, EXPORT_PASSWORD, None, &transport)
+        .await
+        .unwrap();
+    assert_eq!(again.meta.origin, "imported");
+    assert_eq!(again.meta.label, None);
+}
+
+#[tokio::test]
+async fn test_single_flipped_byte_is_detected() {
+    let wm = manager().await;
+    let (envelope, transport) = exported(&wm).await;
+    let sender = sender_key(transport.verifying_key());
+    assert!(open(&envelope, &sender).is_ok());
+
+    // 密文翻转一个字节：keystore 自身的 MAC 检查之前就被签名拦下
+    let mut tampered = envelope.clone();
+    let mut ciphertext = hex::decode(envelope["keystore"]["crypto"]["ciphertext"].as_str().unwrap()).unwrap();
+    ciphertext[0] ^= 0x01;
+    tampered["keystore"]["crypto"]["ciphertext"] = json!(hex::encode(&ciphertext));
+    assert!(matches!(open(&tampered, &sender), Err(EnvelopeError::BadSignature)));
+
+    let mut tampered = envelope.clone();
+    tampered["meta"]["label"] = json!("ops hot #3");
+    assert!(matches!(open(&tampered, &sender), Err(EnvelopeError::BadSignature)));
+
+    // 签名本身被改
+    let mut tampered = envelope.clone();
+    let mut value = hex::decode(envelope["signature"]["value"].as_str().unwrap()).unwrap();
+    value[40] ^= 0x80;
+    tampered["signature"]["value"] = json!(hex::encode(&value));
+    assert!(matches!(open(&tampered, &sender), Err(EnvelopeError::BadSignature)));
+
+    // 追加的未知字段同样受签名保护
+    let mut tampered = envelope.clone();
+    tampered["meta"]["note"] = json!("added in transit");
+    assert!(matches!(open(&tampered, &sender), Err(EnvelopeError::BadSignature)));
+
+    let result = wm
+        .import_enveloped_keystore("tampered", &tampered.to_string(), EXPORT_PASSWORD, WALLET_PASSWORD, &sender)
+        .await;
+    assert_eq!(result.unwrap_err().code(), "ENVELOPE_SIGNATURE_INVALID");
+    assert!(wm.get_wallet_by_name("tampered").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_sender_mismatch() {
+    let wm = manager().await;
+    let (envelope, transport) = exported(&wm).await;
+    let stranger = SigningKey::random(&mut OsRng);
+
+    match open(&envelope, &sender_key(stranger.verifying_key())) {
+        Err(EnvelopeError::SenderMismatch { expected, actual }) => {
+            assert_eq!(expected, sender_key(stranger.verifying_key()));
+            assert_eq!(actual, sender_key(transport.verifying_key()));
+        }
+        other => panic!("expected a sender mismatch, got {:?}", other),
+    }
+
+    // 对方把信封用另一把钥匙重新签名：签名有效但不是约定的发送方
+    let mut resigned = envelope.clone();
+    resigned["signature"] = serde_json::to_value(EnvelopeSignature::sign(&envelope, &stranger)).unwrap();
+    assert!(open(&resigned, &sender_key(stranger.verifying_key())).is_ok());
+    assert!(matches!(
+        open(&resigned, &sender_key(transport.verifying_key())),
+        Err(EnvelopeError::SenderMismatch { .. })
+    ));
+
+    assert!(matches!(open(&envelope, "not-a-key"), Err(EnvelopeError::InvalidSenderKey(_))));
+}
+
+#[tokio::test]
+async fn test_missing_meta_fields() {
+    let wm = manager().await;
+    let (envelope, transport) = exported(&wm).await;
+    let sender = sender_key(transport.verifying_key());
+
+    // 由发送方重新签名，确保报的是缺字段而不是签名错误
+    let resign = |mut document: Value| {
+        document["signature"] = serde_json::to_value(EnvelopeSignature::sign(&document, &transport)).unwrap();
+        document
+    };
+    for field in ["tool", "origin", "address"] {
+        let mut document = envelope.clone();
+        document["meta"].as_object_mut().unwrap().remove(field);
+        let err = open(&resign(document.clone()), &sender).unwrap_err();
+        assert!(matches!(err, EnvelopeError::MissingMeta(f) if f == field), "{}: {:?}", field, err);
+        assert_eq!(err.code(), "ENVELOPE_META_MISSING");
+
+        document["meta"][field] = json!("  ");
+        assert!(matches!(open(&resign(document), &sender), Err(EnvelopeError::MissingMeta(f)) if f == field));
+    }
+    let mut document = envelope.clone();
+    document.as_object_mut().unwrap().remove("meta");
+    assert!(matches!(open(&resign(document), &sender), Err(EnvelopeError::MissingMeta("meta"))));
+
+    // label 可选
+    let mut document = envelope.clone();
+    document["meta"].as_object_mut().unwrap().remove("label");
+    assert!(open(&resign(document), &sender).is_ok());
+
+    // 地址必须是 EIP-55 校验和形式，并与 keystore 一致
+    let mut document = envelope.clone();
+    let lowercase = envelope["meta"]["address"].as_str().unwrap().to_lowercase();
+    document["meta"]["address"] = json!(lowercase);
+    assert!(matches!(open(&resign(document), &sender), Err(EnvelopeError::Malformed(_))));
+    let mut document = envelope.clone();
+    document["meta"]["address"] = json!("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+    assert!(matches!(open(&resign(document), &sender), Err(EnvelopeError::Malformed(_))));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_http_import_records_meta_and_export_signs() {
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config(),
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let import = |name: &str, sender: &str| {
+        app.post("/api/wallets/import_enveloped_keystore").add_header("X-API-KEY", API_KEY).json(&json!({
+            "name": name,
+            "envelope": PARTNER_ENVELOPE,
+            "keystore_password": FIXTURE_PASSWORD,
+            "wallet_password": WALLET_PASSWORD,
+            "expected_sender": sender
+        }))
+    };
+
+    let stranger = sender_key(SigningKey::random(&mut OsRng).verifying_key());
+    let res = import("partner", &stranger).await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(res.json::<Value>()["error"]["code"], "ENVELOPE_SENDER_MISMATCH");
+
+    let res = import("partner", PARTNER_SENDER).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["address"], FIXTURE_ADDRESS);
+    assert_eq!(body["wallet_type"], "imported_key");
+    assert_eq!(body["metadata"]["keystore.tool"], "partner-vault/2.3.1");
+    assert_eq!(body["metadata"]["keystore.sender"], PARTNER_SENDER);
+    let notes = storage.wallet_notes("partner").await.unwrap().unwrap();
+    assert_eq!(notes.metadata["keystore.origin"], "bip32-master");
+    assert_eq!(notes.metadata["keystore.label"], "Treasury cold 7 — Zürich");
+    assert_eq!(notes.metadata["keystore.address"], "0x008AeEda4D805471dF9b2A5B0f38A0C3bCBA786b");
+
+    let res = import("partner", PARTNER_SENDER).await;
+    res.assert_status(StatusCode::CONFLICT);
+    assert_eq!(res.json::<Value>()["error"]["code"], "WALLET_EXISTS");
+
+    let export = || {
+        app.post("/api/wallets/partner/export_enveloped_keystore")
+            .add_header("X-API-KEY", API_KEY)
+            .json(&json!({ "wallet_password": WALLET_PASSWORD, "export_password": EXPORT_PASSWORD, "label": "back" }))
+    };
+    std::env::remove_var("KEYSTORE_TRANSPORT_KEY");
+    let res = export().await;
+    res.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.json::<Value>()["error"]["code"], "TRANSPORT_KEY_UNAVAILABLE");
+
+    let transport = SigningKey::random(&mut OsRng);
+    std::env::set_var("KEYSTORE_TRANSPORT_KEY", hex::encode(transport.to_bytes()));
+    let res = export().await;
+    std::env::remove_var("KEYSTORE_TRANSPORT_KEY");
+    res.assert_status_ok();
+    let envelope: Value = res.json();
+    assert_eq!(envelope["meta"]["origin"], "imported");
+    assert_eq!(envelope["meta"]["label"], "back");
+    let opened = KeystoreEnvelope::open(&envelope.to_string(), &sender_key(transport.verifying_key())).unwrap();
+    assert_eq!(opened.meta.address, "0x008AeEda4D805471dF9b2A5B0f38A0C3bCBA786b");
+}
@@ -0,0 +1,203 @@
+//! keystore V3 导入/导出集成测试
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::core::WalletKeyKind;
+use defi_hot_wallet::crypto::keystore_v3::{KdfParams, KeystoreV3, DEFAULT_SCRYPT_N};
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "keystore-v3-test-api-key-0123456789ab";
+// Web3 Secret Storage 测试向量：Password "testpassword"
+const FIXTURE_PASSWORD: &str = "testpassword";
+const FIXTURE_ADDRESS: &str = "0x008aeeda4d805471df9b2a5b0f38a0c3bcba786b";
+const FIXTURE_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+const SCRYPT_FIXTURE: &str = include_str!("fixtures/keystore/scrypt_testvector.json");
+const PBKDF2_FIXTURE: &str = include_str!("fixtures/keystore/pbkdf2_testvector.json");
+const WALLET_PASSWORD: &str = "Keyst0reWallet";
+
+async fn build_server() -> WalletServer {
+    let mut config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    // 测试中降低导出成本；与 hot_wallet 启动时一样从 [security] 段读取
+    let doc: toml::Value = toml::from_str("[security]\nkeystore_scrypt_n = 1024\n").unwrap();
+    config.security = doc["security"].clone().try_into().unwrap();
+    WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap()
+}
+
+#[test]
+fn test_default_export_cost_matches_geth() {
+    assert_eq!(DEFAULT_SCRYPT_N, 262_144);
+    assert_eq!(WalletConfig::default().security.keystore_scrypt_n, DEFAULT_SCRYPT_N);
+}
+
+#[tokio::test]
+async fn test_import_scrypt_fixture_via_api() {
+    let server = build_server().await;
+    let manager = server.wallet_manager.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let keystore: Value = serde_json::from_str(SCRYPT_FIXTURE).unwrap();
+    let res = app
+        .post("/api/wallets/import_keystore")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({
+            "name": "scrypt_import",
+            "keystore": keystore,
+            "keystore_password": FIXTURE_PASSWORD,
+            "wallet_password": WALLET_PASSWORD
+        }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["address"], FIXTURE_ADDRESS);
+    assert_eq!(body["wallet_type"], "imported_key");
+
+    let stored = manager.get_wallet_by_name("scrypt_import").await.unwrap().unwrap();
+    assert_eq!(stored.key_kind, WalletKeyKind::ImportedKey);
+    // 以我们自己的方案加密存储，不保留原 keystore
+    assert!(!hex::encode(&stored.encrypted_master_key).contains(FIXTURE_KEY));
+    assert_eq!(
+        manager.get_ethereum_address_from_master_key("scrypt_import", WALLET_PASSWORD).await.unwrap(),
+        FIXTURE_ADDRESS
+    );
+}
+
+#[tokio::test]
+async fn test_import_pbkdf2_fixture() {
+    let server = build_server().await;
+    let address = server
+        .wallet_manager
+        .import_keystore_v3("pbkdf2_import", PBKDF2_FIXTURE, FIXTURE_PASSWORD, WALLET_PASSWORD)
+        .await
+        .unwrap();
+    assert_eq!(address, FIXTURE_ADDRESS);
+}
+
+#[tokio::test]
+async fn test_wrong_password_fails_mac_check() {
+    let server = build_server().await;
+    let direct = server
+        .wallet_manager
+        .import_keystore_v3("wrong_pw", PBKDF2_FIXTURE, "not-the-password", WALLET_PASSWORD)
+        .await;
+    assert!(matches!(direct, Err(WalletError::DecryptionError(_))));
+
+    let manager = server.wallet_manager.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    let res = app
+        .post("/api/wallets/import_keystore")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({
+            // 文件内容原样作为字符串上传
+            "name": "wrong_pw",
+            "keystore": SCRYPT_FIXTURE,
+            "keystore_password": "not-the-password",
+            "wallet_password": WALLET_PASSWORD
+        }))
+        .await;
+    res.assert_status_bad_request();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "KEYSTORE_MAC_MISMATCH");
+    assert!(manager.get_wallet_by_name("wrong_pw").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_export_then_import_round_trip() {
+    let server = build_server().await;
+    server
+        .wallet_manager
+        .import_keystore_v3("origin", SCRYPT_FIXTURE, FIXTURE_PASSWORD, WALLET_PASSWORD)
+        .await
+        .unwrap();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let res = app
+        .post("/api/wallets/origin/export_keystore")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "wallet_password": WALLET_PASSWORD, "export_password": "Exp0rtedFile" }))
+        .await;
+    res.assert_status_ok();
+    let exported: Value = res.json();
+    assert_eq!(exported["version"], 3);
+    assert_eq!(exported["address"], FIXTURE_ADDRESS.trim_start_matches("0x"));
+    assert_eq!(exported["crypto"]["cipher"], "aes-128-ctr");
+    assert_eq!(exported["crypto"]["kdf"], "scrypt");
+    assert_eq!(exported["crypto"]["kdfparams"]["n"], 1024);
+    assert_eq!(exported["crypto"]["kdfparams"]["r"], 8);
+    assert_eq!(exported["crypto"]["kdfparams"]["p"], 1);
+
+    // 标准格式：可被独立解析并解出同一私钥
+    let parsed = KeystoreV3::from_json(&exported.to_string()).unwrap();
+    assert!(matches!(parsed.crypto.kdfparams, KdfParams::Scrypt { .. }));
+    assert_eq!(hex::encode(&*parsed.decrypt("Exp0rtedFile").unwrap()), FIXTURE_KEY);
+
+    let res = app
+        .post("/api/wallets/import_keystore")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({
+            "name": "round_trip",
+            "keystore": exported,
+            "keystore_password": "Exp0rtedFile",
+            "wallet_password": WALLET_PASSWORD
+        }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["address"], FIXTURE_ADDRESS);
+
+    // 两次导出使用不同的 salt / iv / uuid
+    let again: Value = app
+        .post("/api/wallets/origin/export_keystore")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "wallet_password": WALLET_PASSWORD, "export_password": "Exp0rtedFile" }))
+        .await
+        .json();
+    assert_ne!(again["id"], exported["id"]);
+    assert_ne!(again["crypto"]["kdfparams"]["salt"], exported["crypto"]["kdfparams"]["salt"]);
+    assert_ne!(again["crypto"]["cipherparams"]["iv"], exported["crypto"]["cipherparams"]["iv"]);
+}
+
+#[tokio::test]
+async fn test_export_requires_wallet_password_and_api_key() {
+    let server = build_server().await;
+    server
+        .wallet_manager
+        .import_keystore_v3("guarded", SCRYPT_FIXTURE, FIXTURE_PASSWORD, WALLET_PASSWORD)
+        .await
+        .unwrap();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let res = app
+        .post("/api/wallets/guarded/export_keystore")
+        .json(&json!({ "wallet_password": WALLET_PASSWORD, "export_password": "Exp0rtedFile" }))
+        .await;
+    res.assert_status_unauthorized();
+
+    let res = app
+        .post("/api/wallets/guarded/export_keystore")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "wallet_password": "Wr0ngWallet", "export_password": "Exp0rtedFile" }))
+        .await;
+    res.assert_status_unauthorized();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "INVALID_PASSWORD");
+}
@@ -507,6 +507,10 @@ fn test_default_blockchain_config() {
         name: "Ethereum Mainnet".to_string(),
         rpc_url: "https://eth.llamarpc.com".to_string(),
         chain_id: 1,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     let config = BlockchainConfig {
@@ -524,6 +528,7 @@ fn test_storage_config_defaults() {
         database_url: "sqlite://./wallets.db".to_string(),
         max_connections: Some(10),
         connection_timeout_seconds: Some(30),
+        read_database_url: None,
     };
     
     assert!(config.database_url.contains("sqlite"));
@@ -606,6 +611,10 @@ fn test_network_config_ethereum() {
         name: "Ethereum Mainnet".to_string(),
         rpc_url: "https://eth.llamarpc.com".to_string(),
         chain_id: 1,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     };
     
     assert_eq!(config.chain_id, 1);
@@ -619,6 +628,10 @@ fn test_network_config_polygon() {
         name: "Polygon Mainnet".to_string(),
         rpc_url: "https://polygon-rpc.com".to_string(),
         chain_id: 137,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     };
     
     assert_eq!(config.chain_id, 137);
@@ -0,0 +1,327 @@
+//! EIP-2771 元transaction中继：sign validate、execute 编码、配额与过期拒绝
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::signers::LocalWallet;
+use ethers::types::{Address, Bytes, H256, U256};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use defi_hot_wallet::anomaly_detection::{AnomalyDetector, DetectionMode};
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::abi::selector_from_signature;
+use defi_hot_wallet::core::config::{RelayConfig, StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::relay::forwarder::EXECUTE_SIGNATURE;
+use defi_hot_wallet::relay::{
+    encode_execute, ForwardRequest, ForwarderDomain, RelayError, RelayService, RelaySubmitter,
+};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{WalletStorage, RELAY_FAILED, RELAY_SUBMITTED};
+
+const FIXTURE: &str = include_str!("fixtures/relay/forward_request.json");
+/// Key that produced the fixture signature
+const SIGNER_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const API_KEY: &str = "meta-tx-relay-test-key-0123456789";
+
+struct Fixture {
+    domain: ForwarderDomain,
+    request: ForwardRequest,
+    signature: Vec<u8>,
+    raw: Value,
+}
+
+fn unhex(s: &str) -> Vec<u8> {
+    hex::decode(s.trim_start_matches("0x")).unwrap()
+}
+
+fn fixture() -> Fixture {
+    let raw: Value = serde_json::from_str(FIXTURE).unwrap();
+    let domain = ForwarderDomain {
+        name: raw["domain"]["name"].as_str().unwrap().to_string(),
+        version: raw["domain"]["version"].as_str().unwrap().to_string(),
+        chain_id: raw["domain"]["chainId"].as_u64().unwrap(),
+        verifying_contract: raw["domain"]["verifyingContract"].as_str().unwrap().parse().unwrap(),
+    };
+    Fixture {
+        domain,
+        request: serde_json::from_value(raw["request"].clone()).unwrap(),
+        signature: unhex(raw["signature"].as_str().unwrap()),
+        raw,
+    }
+}
+
+fn relay_config(fx: &Fixture, quota: u32) -> RelayConfig {
+    RelayConfig {
+        enabled: true,
+        chain_id: fx.domain.chain_id,
+        forwarder_address: format!("{:?}", fx.domain.verifying_contract),
+        quota_per_address: quota,
+        ..Default::default()
+    }
+}
+
+fn sign(domain: &ForwarderDomain, request: &ForwardRequest, key: &str) -> Vec<u8> {
+    let wallet: LocalWallet = key.parse().unwrap();
+    wallet.sign_hash(H256::from(domain.digest(request))).unwrap().to_vec()
+}
+
+/// Records submissions instead of broadcasting them
+#[derive(Default)]
+struct RecordingSubmitter {
+    calls: Mutex<Vec<(Address, u64, Vec<u8>)>>,
+    fail: bool,
+}
+
+#[async_trait]
+impl RelaySubmitter for RecordingSubmitter {
+    async fn submit(
+        &self,
+        forwarder: Address,
+        _value: U256,
+        gas_limit: u64,
+        calldata: Vec<u8>,
+    ) -> Result<String, WalletError> {
+        if self.fail {
+            return Err(WalletError::NetworkError("rpc unavailable".to_string()));
+        }
+        let mut calls = self.calls.lock().unwrap();
+        calls.push((forwarder, gas_limit, calldata));
+        Ok(format!("0x{:064x}", calls.len()))
+    }
+}
+
+async fn service(
+    dir: &TempDir,
+    config: RelayConfig,
+    submitter: Arc<RecordingSubmitter>,
+) -> (RelayService, Arc<WalletStorage>) {
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("relay.db").display());
+    let storage = Arc::new(WalletStorage::new_with_url(&url).await.unwrap());
+    // 只验证中继流程本身；异常规则单独测试
+    let mut detector = AnomalyDetector::new();
+    detector.set_mode(DetectionMode::WarnOnly);
+    let service = RelayService::new(config, storage.clone(), submitter).unwrap().with_detector(detector);
+    (service, storage)
+}
+
+#[test]
+fn test_signature_matches_ethers_fixture() {
+    let fx = fixture();
+    assert_eq!(fx.domain.separator().to_vec(), unhex(fx.raw["domainSeparator"].as_str().unwrap()));
+    assert_eq!(fx.domain.digest(&fx.request).to_vec(), unhex(fx.raw["digest"].as_str().unwrap()));
+    assert_eq!(fx.domain.recover_signer(&fx.request, &fx.signature), Some(fx.request.from));
+
+    // any field change, or another forwarder/chain, breaks the signature
+    let mut tampered = fx.request.clone();
+    tampered.nonce = U256::from(8);
+    assert_ne!(fx.domain.recover_signer(&tampered, &fx.signature), Some(fx.request.from));
+    let other_chain = ForwarderDomain { chain_id: 1, ..fx.domain.clone() };
+    assert_ne!(other_chain.recover_signer(&fx.request, &fx.signature), Some(fx.request.from));
+
+    // malleable twin (s' = n - s, flipped v) is rejected
+    let n = U256::from_str_radix("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141", 16).unwrap();
+    let s = U256::from_big_endian(&fx.signature[32..64]);
+    let mut twin = fx.signature.clone();
+    (n - s).to_big_endian(&mut twin[32..64]);
+    twin[64] = if twin[64] == 27 { 28 } else { 27 };
+    assert_eq!(fx.domain.recover_signer(&fx.request, &twin), None);
+}
+
+#[test]
+fn test_execute_calldata_encoding() {
+    let fx = fixture();
+    let calldata = encode_execute(&fx.request, &fx.signature);
+    let expected = unhex(fx.raw["executeCalldata"].as_str().unwrap());
+    assert_eq!(calldata, expected);
+    assert_eq!(calldata[..4], selector_from_signature(EXECUTE_SIGNATURE));
+    // selector + 2 head words + 7 tuple words + data (len + 3 words) + signature (len + 3 words)
+    assert_eq!(calldata.len(), 4 + 32 * (2 + 7 + 4 + 4));
+
+    // empty data still gets its length word
+    let empty = ForwardRequest { data: Bytes::new(), ..fx.request.clone() };
+    assert_eq!(encode_execute(&empty, &fx.signature).len(), 4 + 32 * (2 + 7 + 1 + 4));
+}
+
+#[tokio::test]
+async fn test_relay_submits_and_links_sender() {
+    let fx = fixture();
+    let dir = TempDir::new().unwrap();
+    let submitter = Arc::new(RecordingSubmitter::default());
+    let (service, storage) = service(&dir, relay_config(&fx, 5), submitter.clone()).await;
+
+    let receipt = service.relay(&fx.request, &fx.signature).await.unwrap();
+    {
+        let calls = submitter.calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].0, fx.domain.verifying_contract);
+        assert_eq!(calls[0].1, 100_000 + defi_hot_wallet::relay::EXECUTE_GAS_OVERHEAD);
+        assert_eq!(calls[0].2, encode_execute(&fx.request, &fx.signature));
+    }
+
+    let from = format!("{:?}", fx.request.from);
+    let relays = storage.meta_tx_relays_for(&from, 10).await.unwrap();
+    assert_eq!(relays.len(), 1);
+    assert_eq!(relays[0].id, receipt.relay_id);
+    assert_eq!(relays[0].status, RELAY_SUBMITTED);
+    assert_eq!(relays[0].tx_hash.as_deref(), Some(receipt.tx_hash.as_str()));
+    assert_eq!(relays[0].request_nonce, "7");
+
+    // the same request cannot be relayed twice
+    let err = service.relay(&fx.request, &fx.signature).await.unwrap_err();
+    assert!(matches!(err, RelayError::Duplicate), "{:?}", err);
+    assert_eq!(submitter.calls.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_quota_is_enforced_per_sender() {
+    let fx = fixture();
+    let dir = TempDir::new().unwrap();
+    let submitter = Arc::new(RecordingSubmitter::default());
+    let (service, _storage) = service(&dir, relay_config(&fx, 2), submitter.clone()).await;
+
+    let signed = |nonce: u64, key: &str| {
+        let wallet: LocalWallet = key.parse().unwrap();
+        let request = ForwardRequest {
+            from: ethers::signers::Signer::address(&wallet),
+            nonce: U256::from(nonce),
+            ..fx.request.clone()
+        };
+        let signature = sign(&fx.domain, &request, key);
+        (request, signature)
+    };
+
+    for nonce in [1, 2] {
+        let (request, signature) = signed(nonce, SIGNER_KEY);
+        service.relay(&request, &signature).await.unwrap();
+    }
+    let (request, signature) = signed(3, SIGNER_KEY);
+    let err = service.relay(&request, &signature).await.unwrap_err();
+    assert!(matches!(err, RelayError::QuotaExhausted { limit: 2, .. }), "{:?}", err);
+    assert_eq!(err.code(), "RELAY_QUOTA_EXHAUSTED");
+
+    // duplicates do not consume quota, and other senders are unaffected
+    let other_key = "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+    let (request, signature) = signed(1, other_key);
+    service.relay(&request, &signature).await.unwrap();
+    assert_eq!(submitter.calls.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_failed_submission_refunds_quota() {
+    let fx = fixture();
+    let dir = TempDir::new().unwrap();
+    let failing = Arc::new(RecordingSubmitter { fail: true, ..Default::default() });
+    let (service, storage) = service(&dir, relay_config(&fx, 1), failing).await;
+
+    let err = service.relay(&fx.request, &fx.signature).await.unwrap_err();
+    assert_eq!(err.code(), "RELAY_SUBMISSION_FAILED");
+    let from = format!("{:?}", fx.request.from);
+    let relays = storage.meta_tx_relays_for(&from, 10).await.unwrap();
+    assert_eq!(relays[0].status, RELAY_FAILED);
+    assert!(relays[0].tx_hash.is_none());
+
+    // quota of 1 is still available, and the failed attempt does not block a retry
+    assert!(storage.relay_consume_quota(&from, 1, 3600).await.unwrap());
+    storage.relay_release_quota(&from).await.unwrap();
+    let ok = Arc::new(RecordingSubmitter::default());
+    let retry = RelayService::new(relay_config(&fx, 1), storage.clone(), ok).unwrap();
+    let mut detector = AnomalyDetector::new();
+    detector.set_mode(DetectionMode::WarnOnly);
+    retry.with_detector(detector).relay(&fx.request, &fx.signature).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_rejections_are_distinct() {
+    let fx = fixture();
+    let dir = TempDir::new().unwrap();
+    let submitter = Arc::new(RecordingSubmitter::default());
+    let (service, storage) = service(&dir, relay_config(&fx, 5), submitter.clone()).await;
+
+    // expired: checked before the signature
+    let expired = ForwardRequest { valid_until: U256::from(1_600_000_000u64), ..fx.request.clone() };
+    let err = service.relay(&expired, &fx.signature).await.unwrap_err();
+    assert!(matches!(err, RelayError::Expired { valid_until: 1_600_000_000 }), "{:?}", err);
+    assert_eq!(err.code(), "META_TX_EXPIRED");
+
+    // signed by someone other than `from`
+    let forged = sign(&fx.domain, &fx.request, "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d");
+    let err = service.relay(&fx.request, &forged).await.unwrap_err();
+    assert_eq!(err.code(), "SIGNATURE_MISMATCH");
+
+    let too_much_gas = ForwardRequest { gas: U256::from(5_000_000u64), ..fx.request.clone() };
+    let err = service.relay(&too_much_gas, &fx.signature).await.unwrap_err();
+    assert_eq!(err.code(), "INVALID_FORWARD_REQUEST");
+
+    // blacklisted target is blocked by the anomaly rules
+    let mut detector = AnomalyDetector::new();
+    detector.add_to_blacklist(format!("{:?}", fx.request.to), "test".to_string());
+    let guarded = RelayService::new(relay_config(&fx, 5), storage, submitter.clone()).unwrap().with_detector(detector);
+    let err = guarded.relay(&fx.request, &fx.signature).await.unwrap_err();
+    assert_eq!(err.code(), "ANOMALY_BLOCKED");
+
+    assert!(submitter.calls.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_relay_endpoint_rejections() {
+    let fx = fixture();
+    let build = |relay: RelayConfig| async move {
+        let config = WalletConfig {
+            storage: StorageConfig {
+                database_url: "sqlite::memory:".to_string(),
+                max_connections: Some(5),
+                connection_timeout_seconds: Some(10),
+                read_database_url: None,
+            },
+            relay,
+            ..Default::default()
+        };
+        let server = WalletServer::new_for_test(
+            "127.0.0.1".to_string(),
+            0,
+            config,
+            Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+            None,
+        )
+        .await
+        .unwrap();
+        TestServer::new(server.create_router().await).unwrap()
+    };
+    let body = |request: &Value, signature: &str| json!({ "request": request, "signature": signature });
+    let signature = fx.raw["signature"].as_str().unwrap();
+
+    let disabled = build(RelayConfig::default()).await;
+    disabled.post("/api/relay/meta_tx").json(&body(&fx.raw["request"], signature)).await.assert_status_unauthorized();
+    let res = disabled
+        .post("/api/relay/meta_tx")
+        .add_header("Authorization", API_KEY)
+        .json(&body(&fx.raw["request"], signature))
+        .await;
+    res.assert_status(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.json::<Value>()["error"]["code"], "RELAY_DISABLED");
+
+    let app = build(relay_config(&fx, 5)).await;
+    let mut expired = fx.raw["request"].clone();
+    expired["validUntil"] = json!("1600000000");
+    let res = app.post("/api/relay/meta_tx").add_header("Authorization", API_KEY).json(&body(&expired, signature)).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "META_TX_EXPIRED");
+
+    let mut bumped = fx.raw["request"].clone();
+    bumped["nonce"] = json!("8");
+    let res = app.post("/api/relay/meta_tx").add_header("Authorization", API_KEY).json(&body(&bumped, signature)).await;
+    res.assert_status(axum::http::StatusCode::FORBIDDEN);
+    assert_eq!(res.json::<Value>()["error"]["code"], "SIGNATURE_MISMATCH");
+
+    let res = app.post("/api/relay/meta_tx").add_header("Authorization", API_KEY).json(&body(&fx.raw["request"], "0x1234")).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_FORWARD_REQUEST");
+
+    let from = fx.raw["request"]["from"].as_str().unwrap();
+    let res = app.get(&format!("/api/relay/meta_tx?from={}", from)).add_header("Authorization", API_KEY).await;
+    res.assert_status_ok();
+    assert!(res.json::<Value>()["relays"].as_array().unwrap().is_empty());
+}
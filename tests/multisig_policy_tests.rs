@@ -0,0 +1,181 @@
+//! 分档多签策略（`/api/wallets/:name/multisig/*`）集成测试
+
+mod util;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde_json::{json, Value};
+
+
+const API_KEY: &str = "multisig-policy-admin-key";
+const SESSION: &str = "multisig-policy-session";
+const OTHER_SESSION: &str = "multisig-policy-other-session";
+const ADDRESS: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+const ONE_ETH: &str = "1000000000000000000";
+
+async fn build() -> (TestServer, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY)).await;
+
+    for (email, session, wallet) in
+        [("vault@example.com", SESSION, Some("vault")), ("outsider@example.com", OTHER_SESSION, None)]
+    {
+        let user_id = util::sign_in(&server, email, session).await;
+        if let Some(wallet) = wallet {
+            server.user_db.link_wallet(&user_id, wallet, ADDRESS, None).await.unwrap();
+        }
+    }
+
+    (TestServer::new(server.create_router().await).unwrap(), dir)
+}
+
+fn signers() -> Vec<String> {
+    let secp = Secp256k1::new();
+    (1..=3u8)
+        .map(|seed| {
+            let secret = SecretKey::from_slice(&[seed; 32]).unwrap();
+            hex::encode(PublicKey::from_secret_key(&secp, &secret).serialize())
+        })
+        .collect()
+}
+
+/// 1 ETH 以内 1 个sign，10 ETH 以内 2 个，100 ETH 以内 3 个
+fn tiered_policy() -> Value {
+    json!({
+        "network": "eth",
+        "signers": signers(),
+        "tiers": [
+            { "max_amount": ONE_ETH, "required": 1 },
+            { "max_amount": format!("{}0", ONE_ETH), "required": 2 },
+            { "max_amount": format!("{}00", ONE_ETH), "required": 3 },
+        ],
+    })
+}
+
+async fn propose(app: &TestServer, amount: &str) -> Value {
+    let res = app
+        .post("/api/wallets/vault/multisig/proposals")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "to": RECIPIENT, "amount": amount, "network": "eth" }))
+        .await;
+    res.assert_status_ok();
+    res.json()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_policy_management_requires_owner_or_admin() {
+    let (app, _dir) = build().await;
+
+    app.put("/api/wallets/vault/multisig/policy")
+        .json(&tiered_policy())
+        .await
+        .assert_status_unauthorized();
+    let res = app
+        .put("/api/wallets/vault/multisig/policy")
+        .add_header("Authorization", format!("Bearer {}", OTHER_SESSION))
+        .json(&tiered_policy())
+        .await;
+    res.assert_status(StatusCode::FORBIDDEN);
+
+    let res = app
+        .put("/api/wallets/vault/multisig/policy")
+        .add_header("Authorization", API_KEY)
+        .json(&tiered_policy())
+        .await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["updated_by"], "admin");
+
+    let res = app
+        .get("/api/wallets/vault/multisig/policy?network=eth")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["tiers"][0], json!({ "max_amount": ONE_ETH, "required": 1 }));
+    assert_eq!(body["signers"].as_array().unwrap().len(), 3);
+
+    app.get("/api/wallets/vault/multisig/policy?network=polygon")
+        .add_header("Authorization", API_KEY)
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_invalid_policies_are_rejected() {
+    let (app, _dir) = build().await;
+    let put = |body: Value| {
+        app.put("/api/wallets/vault/multisig/policy")
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .json(&body)
+    };
+
+    // 金额更大的档位阈值反而更低
+    let mut non_monotonic = tiered_policy();
+    non_monotonic["tiers"][1]["required"] = json!(1);
+    non_monotonic["tiers"][0]["required"] = json!(2);
+    let res = put(non_monotonic).await;
+    res.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_MULTISIG_POLICY");
+
+    // 阈值超过sign者数
+    let mut too_many = tiered_policy();
+    too_many["signers"] = json!(signers()[..2]);
+    put(too_many).await.assert_status(StatusCode::BAD_REQUEST);
+
+    let mut duplicate = tiered_policy();
+    duplicate["signers"] = json!([signers()[0], signers()[0], signers()[1]]);
+    put(duplicate).await.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_proposals_freeze_required_signatures() {
+    let (app, _dir) = build().await;
+    app.put("/api/wallets/vault/multisig/policy")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&tiered_policy())
+        .await
+        .assert_status_ok();
+
+    // 恰好等于档位上限时归入该档
+    assert_eq!(propose(&app, "1").await["required_signatures"], 1);
+    let medium = propose(&app, "1.000000000000000001").await;
+    assert_eq!(medium["required_signatures"], 2);
+    assert_eq!(medium["amount"], "1000000000000000001");
+    assert_eq!(propose(&app, "100").await["required_signatures"], 3);
+
+    let res = app
+        .post("/api/wallets/vault/multisig/proposals")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "to": RECIPIENT, "amount": "100.1", "network": "eth" }))
+        .await;
+    res.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(res.json::<Value>()["error"]["code"], "AMOUNT_EXCEEDS_POLICY");
+
+    // 放宽策略后，已有提案的要求不变，详情中仍是创建时的策略
+    let relaxed = json!({
+        "network": "eth",
+        "signers": signers(),
+        "tiers": [{ "max_amount": format!("{}000", ONE_ETH), "required": 1 }],
+    });
+    app.put("/api/wallets/vault/multisig/policy")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&relaxed)
+        .await
+        .assert_status_ok();
+
+    let res = app
+        .get(&format!("/api/wallets/vault/multisig/proposals/{}", medium["id"].as_str().unwrap()))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await;
+    res.assert_status_ok();
+    let detail: Value = res.json();
+    assert_eq!(detail["required_signatures"], 2);
+    assert_eq!(detail["signature_count"], 0);
+    assert_eq!(detail["policy"]["tiers"].as_array().unwrap().len(), 3);
+    assert_eq!(propose(&app, "1.000000000000000001").await["required_signatures"], 1);
+}
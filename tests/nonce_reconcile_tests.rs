@@ -0,0 +1,215 @@
+//! nonce 对账：预留三个 nonce、链上只"确认"一个时，其余两个被归还、计数器回退；
+//! 预留过新或没有预留记录的 nonce 作为 gap 报告；`POST /api/wallets/:name/nonces/reconcile`
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, H256};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{WalletStorage, WalletStorageTrait};
+
+const API_KEY: &str = "nonce-reconcile-admin-key";
+const WALLET: &str = "stuck";
+const PASSWORD: &str = "N0nce!Reconcile#2024";
+
+/// 链上transaction数由测试设定
+#[derive(Default)]
+struct MockChain {
+    count: Mutex<Option<u64>>,
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, _tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_count(&self, network: &str, _address: Address) -> Result<u64, WalletError> {
+        self.count.lock().unwrap().ok_or_else(|| WalletError::NetworkError(format!("{} unreachable", network)))
+    }
+}
+
+#[tokio::test]
+async fn test_release_only_from_the_top() {
+    let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
+    let address = "0xabc0000000000000000000000000000000000001";
+
+    assert_eq!(storage.reserve_nonce_range("eth", address, 0, 3).await.unwrap(), 0);
+    let nonces = |list: Vec<defi_hot_wallet::storage::NonceReservation>| -> Vec<i64> {
+        list.into_iter().map(|r| r.nonce).collect()
+    };
+    assert_eq!(nonces(storage.list_reserved_unconfirmed_nonces("eth", address).await.unwrap()), [0, 1, 2]);
+
+    // 链上只计入了 nonce 0
+    assert_eq!(storage.confirm_nonces_below("eth", address, 1).await.unwrap(), 1);
+    assert_eq!(nonces(storage.list_reserved_unconfirmed_nonces("eth", address).await.unwrap()), [1, 2]);
+
+    // 只能从最高的预留往下归还；已确认的不能归还
+    assert!(!storage.release_nonce("eth", address, 1).await.unwrap());
+    assert!(storage.release_nonce("eth", address, 2).await.unwrap());
+    assert!(!storage.release_nonce("eth", address, 2).await.unwrap());
+    assert!(storage.release_nonce("eth", address, 1).await.unwrap());
+    assert!(!storage.release_nonce("eth", address, 0).await.unwrap());
+    assert_eq!(storage.next_nonce("eth", address).await.unwrap(), Some(1));
+    assert!(storage.list_reserved_unconfirmed_nonces("eth", address).await.unwrap().is_empty());
+
+    // 归还的 nonce 再次被分配
+    assert_eq!(storage.reserve_next_nonce("eth", address, 0).await.unwrap(), 1);
+    assert_eq!(nonces(storage.list_reserved_unconfirmed_nonces("eth", address).await.unwrap()), [1]);
+
+    // release_nonce_range 归还的尾部不再算作预留
+    assert_eq!(storage.reserve_nonce_range("eth", address, 0, 3).await.unwrap(), 2);
+    assert!(storage.release_nonce_range("eth", address, 3, 5).await.unwrap());
+    assert_eq!(nonces(storage.list_reserved_unconfirmed_nonces("eth", address).await.unwrap()), [1, 2]);
+}
+
+struct Harness {
+    app: TestServer,
+    chain: Arc<MockChain>,
+    storage: Arc<WalletStorage>,
+    address: String,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let mut config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    config.security.pbkdf2_iterations = 1_000;
+    let chain = Arc::new(MockChain::default());
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap()
+    .with_broadcast_chain(chain.clone());
+    server.wallet_manager.create_wallet(WALLET, PASSWORD, false).await.unwrap();
+    let address = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, chain, storage, address: format!("{:#x}", address), _dir: dir }
+}
+
+impl Harness {
+    async fn reconcile(&self, body: Value, password: Option<&str>) -> axum_test::TestResponse {
+        let mut req =
+            self.app.post(&format!("/api/wallets/{}/nonces/reconcile", WALLET)).add_header("X-API-KEY", API_KEY);
+        if let Some(password) = password {
+            req = req.add_header("X-Wallet-Password", password);
+        }
+        req.json(&body).await
+    }
+
+    fn set_on_chain(&self, count: u64) {
+        *self.chain.count.lock().unwrap() = Some(count);
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_dropped_nonces_are_released() {
+    let h = build().await;
+    // 三笔发送预留了 0、1、2，只有第一笔上链，其余被 mempool 丢弃
+    assert_eq!(h.storage.reserve_nonce_range("eth", &h.address, 0, 3).await.unwrap(), 0);
+    h.set_on_chain(1);
+
+    let res = h.reconcile(json!({ "network": "eth", "min_age_secs": 0 }), Some(PASSWORD)).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["address"], h.address);
+    assert_eq!(body["on_chain"], 1);
+    assert_eq!(body["stored_next"], 3);
+    assert_eq!(body["released"], json!([2, 1]));
+    assert_eq!(body["gaps"], json!([]));
+
+    // 下一笔发送接着链上的 nonce
+    assert_eq!(h.storage.next_nonce("eth", &h.address).await.unwrap(), Some(1));
+    assert!(h.storage.list_reserved_unconfirmed_nonces("eth", &h.address).await.unwrap().is_empty());
+    assert_eq!(h.storage.reserve_nonce_range("eth", &h.address, 1, 1).await.unwrap(), 1);
+
+    // 已一致时什么也不做
+    h.set_on_chain(2);
+    let body: Value = h.reconcile(json!({ "min_age_secs": 0 }), Some(PASSWORD)).await.json();
+    assert_eq!(body["on_chain"], 2);
+    assert_eq!(body["stored_next"], 2);
+    assert_eq!(body["released"], json!([]));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_gaps_are_reported() {
+    let h = build().await;
+    assert_eq!(h.storage.reserve_nonce_range("eth", &h.address, 0, 3).await.unwrap(), 0);
+    h.set_on_chain(1);
+
+    // 默认 min_age：刚预留的 nonce 可能还在广播途中，不归还
+    let body: Value = h.reconcile(json!({ "network": "eth" }), Some(PASSWORD)).await.json();
+    assert_eq!(body["released"], json!([]));
+    assert_eq!(body["gaps"], json!([1, 2]));
+    assert_eq!(h.storage.next_nonce("eth", &h.address).await.unwrap(), Some(3));
+
+    // 计数器被抬到没有预留记录的 nonce：顶部无法归还，整段报告为 gap
+    h.storage.mark_nonce_used("eth", &h.address, 4).await.unwrap();
+    let body: Value = h.reconcile(json!({ "min_age_secs": 0 }), Some(PASSWORD)).await.json();
+    assert_eq!(body["stored_next"], 5);
+    assert_eq!(body["released"], json!([]));
+    assert_eq!(body["gaps"], json!([1, 2, 3, 4]));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_reconcile_errors() {
+    let h = build().await;
+
+    let res = h.reconcile(json!({ "network": "eth" }), None).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "MISSING_PARAMETER");
+
+    let res = h.reconcile(json!({ "network": "btc" }), Some(PASSWORD)).await;
+    res.assert_status_bad_request();
+
+    let res = h.reconcile(json!({ "network": "eth" }), Some("Wr0ng!Password")).await;
+    res.assert_status_unauthorized();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_PASSWORD");
+
+    // 节点不可用
+    let res = h.reconcile(json!({ "network": "eth" }), Some(PASSWORD)).await;
+    res.assert_status(axum::http::StatusCode::BAD_GATEWAY);
+    assert_eq!(res.json::<Value>()["error"]["code"], "NONCE_LOOKUP_FAILED");
+
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/nonces/reconcile", WALLET))
+        .add_header("X-Wallet-Password", PASSWORD)
+        .json(&json!({ "network": "eth" }))
+        .await;
+    res.assert_status_unauthorized();
+}
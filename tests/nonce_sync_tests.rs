@@ -12,6 +12,7 @@ mod tests {
                 database_url: "sqlite::memory:".to_string(),
                 max_connections: Some(10),
                 connection_timeout_seconds: Some(30),
+                read_database_url: None,
             },
             blockchain: defi_hot_wallet::core::config::BlockchainConfig {
                 networks: HashMap::new(),
@@ -20,6 +21,27 @@ mod tests {
             multi_sig_threshold: 1,
             derivation: Default::default(),
             security: defi_hot_wallet::core::config::SecurityConfig::default(),
+            bridge_backend: Default::default(),
+            balance_snapshots: Default::default(),
+            backups: Default::default(),
+            relay: Default::default(),
+            cluster: Default::default(),
+            account_abstraction: Default::default(),
+            fee_tracking: Default::default(),
+            approvals: Default::default(),
+            pricing: Default::default(),
+            jobs: Default::default(),
+            wal: Default::default(),
+            database_queries: Default::default(),
+            recipient_guard: Default::default(),
+            reserve_reports: Default::default(),
+            admission: Default::default(),
+            backfill: Default::default(),
+            feature_flags: Default::default(),
+            tokens: Default::default(),
+            timelocks: Default::default(),
+            bridge_poller: Default::default(),
+            anomaly_detection: Default::default(),
         };
 
         let wallet_manager = WalletManager::new(&config).await.unwrap();
@@ -0,0 +1,369 @@
+//! operation bundle：approve → swap → bridge 的占位符替换、失败策略、合计限额与重启后恢复
+
+mod util;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Bytes, TransactionReceipt, H256};
+use serde_json::{json, Value};
+use std::future::IntoFuture;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+use defi_hot_wallet::anomaly_detection::{AnomalyDetector, DetectionMode};
+use defi_hot_wallet::core::config::NetworkConfig;
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::storage::journal_events::{BUNDLE_CREATED, BUNDLE_STATUS_CHANGED, BUNDLE_STEP_STATUS_CHANGED};
+use defi_hot_wallet::storage::{WalletStorage, WalletStorageTrait};
+
+const API_KEY: &str = "operation-bundle-admin-key";
+const TOKEN: &str = "operation-bundle-user-token";
+const EMAIL: &str = "bundler@example.com";
+const WALLET: &str = "bundler";
+const PASSWORD: &str = "Bundl3r!Vault#2024";
+const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+const ROUTER: &str = "0x1111111254eeb25477b68fb85ed929f73a960582";
+const ALICE: &str = "0x000000000000000000000000000000000000a11c";
+const BOB: &str = "0x000000000000000000000000000000000000b0b0";
+
+/// 节点：记录广播次数；`hang_on` 让第 N 次 prepare 永不返回，模拟执行中途进程退出
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    prepared: Mutex<usize>,
+    broadcasts: Mutex<usize>,
+    hang_on: Option<usize>,
+    reached: Notify,
+}
+
+impl MockChain {
+    fn starting_at(nonce: u64) -> Self {
+        Self { nonce: Mutex::new(nonce), ..Default::default() }
+    }
+
+    fn broadcasts(&self) -> usize {
+        *self.broadcasts.lock().unwrap()
+    }
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let call = {
+            let mut prepared = self.prepared.lock().unwrap();
+            *prepared += 1;
+            *prepared
+        };
+        if self.hang_on == Some(call) {
+            self.reached.notify_one();
+            std::future::pending::<()>().await;
+        }
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        *self.broadcasts.lock().unwrap() += 1;
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        _tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        Ok(None)
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    storage: Arc<WalletStorage>,
+}
+
+fn warn_only() -> AnomalyDetector {
+    let mut detector = AnomalyDetector::new();
+    detector.set_mode(DetectionMode::WarnOnly);
+    detector
+}
+
+/// 同一目录再次调用即模拟重启：钱包库与用户库都保留，只重新登记会话
+async fn build(dir: &tempfile::TempDir, chain: Arc<MockChain>, detector: AnomalyDetector) -> Harness {
+    let restart = dir.path().join("wallet.db").exists();
+    let mut config = util::file_config(dir.path());
+    // bridge 步骤只在已配置的network之间路由
+    config.blockchain.networks.insert(
+        "polygon".to_string(),
+        NetworkConfig {
+            name: "polygon".to_string(),
+            rpc_url: "http://127.0.0.1:1".to_string(),
+            chain_id: 137,
+            explorer_url_template: None,
+            rate_limit: None,
+            fallback_endpoints: Vec::new(),
+            pending_expiry_seconds: None,
+        },
+    );
+    let server = util::test_server(dir.path(), config, Some(API_KEY))
+        .await
+        .with_broadcast_chain(chain)
+        .with_bundle_detector(detector);
+
+    let keystore_path = dir.path().join("bundler.keystore");
+    if restart {
+        // wallet管理器只在内存中持有钱包：重启后从 keystore 导回；wallet行已在库中，导入只写临时库
+        let keystore = std::fs::read_to_string(&keystore_path).unwrap();
+        let scratch: Arc<dyn WalletStorageTrait + Send + Sync> =
+            Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap());
+        server.wallet_manager.import_wallet(WALLET, &keystore, PASSWORD, &scratch).await.unwrap();
+        let user_id = sqlx::query_scalar::<_, String>("SELECT id FROM users WHERE email = ?1")
+            .bind(EMAIL)
+            .fetch_one(server.user_db.pool())
+            .await
+            .unwrap();
+        server.session_store.register_token(TOKEN, &user_id, 3600).await;
+    } else {
+        let user_id = util::sign_in(&server, EMAIL, TOKEN).await;
+        util::create_wallet(&server, WALLET, PASSWORD).await;
+        let address = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+        server.user_db.link_wallet(&user_id, WALLET, &format!("{:#x}", address), None).await.unwrap();
+        std::fs::write(&keystore_path, server.wallet_manager.export_wallet(WALLET, PASSWORD).await.unwrap()).unwrap();
+    }
+
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, storage }
+}
+
+fn transfer(id: &str, to: &str, amount: &str) -> Value {
+    json!({ "id": id, "kind": "transfer", "network": "eth", "to": to, "amount": amount })
+}
+
+impl Harness {
+    fn submit_request(&self, body: Value) -> axum_test::TestRequest {
+        self.app
+            .post(&format!("/api/wallets/{}/bundles", WALLET))
+            .add_header("Authorization", format!("Bearer {}", TOKEN))
+            .json(&body)
+    }
+
+    async fn submit(&self, steps: Value) -> axum_test::TestResponse {
+        self.submit_request(json!({ "password": PASSWORD, "steps": steps })).await
+    }
+
+    async fn get(&self, id: &str) -> Value {
+        let res = self
+            .app
+            .get(&format!("/api/bundles/{}", id))
+            .add_header("Authorization", format!("Bearer {}", TOKEN))
+            .await;
+        res.assert_status_ok();
+        res.json()
+    }
+
+    async fn resume(&self, id: &str) -> axum_test::TestResponse {
+        self.app
+            .post(&format!("/api/bundles/{}/resume", id))
+            .add_header("Authorization", format!("Bearer {}", TOKEN))
+            .json(&json!({ "password": PASSWORD }))
+            .await
+    }
+}
+
+fn step<'a>(bundle: &'a Value, id: &str) -> &'a Value {
+    bundle["steps"].as_array().unwrap().iter().find(|s| s["step_id"] == id).unwrap()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_approve_swap_bridge_forwards_the_swap_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let h = build(&dir, Arc::new(MockChain::default()), warn_only()).await;
+
+    let res = h
+        .submit(json!([
+            { "id": "approve", "kind": "approve", "network": "eth", "token": USDC, "spender": ROUTER,
+              "amount": "1000", "decimals": 6 },
+            { "id": "swap", "kind": "swap", "depends_on": ["approve"], "network": "eth", "from_token": "USDC",
+              "to_token": "ETH", "amount": "1000", "slippage": 0.5 },
+            { "id": "bridge", "kind": "bridge", "depends_on": ["swap"], "from_chain": "eth",
+              "to_chain": "polygon", "token": "ETH", "amount": "{{steps.swap.amount}}" },
+        ]))
+        .await;
+    res.assert_status_ok();
+    let bundle: Value = res.json();
+    assert_eq!(bundle["status"], "completed");
+    assert_eq!(bundle["failure_policy"], "abort_remaining");
+    for s in bundle["steps"].as_array().unwrap() {
+        assert_eq!(s["status"], "completed", "{}", s);
+    }
+
+    // 定义保留占位符，resolved 记录实际使用的 swap 产出
+    let bridge = step(&bundle, "bridge");
+    assert_eq!(bridge["definition"]["amount"], "{{steps.swap.amount}}");
+    assert_eq!(bridge["resolved"]["amount"], "0.285714285714285714");
+    assert_eq!(bridge["output"]["network"], "polygon");
+    assert!(bridge["output"]["reference"].is_string());
+    assert_eq!(step(&bundle, "swap")["output"]["amount"], "0.285714285714285714");
+    assert!(step(&bundle, "approve")["output"]["tx_hash"].as_str().unwrap().starts_with("0x"));
+
+    let id = bundle["id"].as_str().unwrap();
+    assert_eq!(h.get(id).await, bundle);
+
+    let events: Vec<_> =
+        h.storage.journal_events(0, 100).await.unwrap().into_iter().filter(|e| e.entity_id == id).collect();
+    assert_eq!(events.first().unwrap().event_type, BUNDLE_CREATED);
+    assert_eq!(events.iter().filter(|e| e.event_type == BUNDLE_STEP_STATUS_CHANGED).count(), 6);
+    let last = events.last().unwrap();
+    assert_eq!(last.event_type, BUNDLE_STATUS_CHANGED);
+    assert_eq!(last.payload["to_status"], "completed");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_failed_step_aborts_the_rest() {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = Arc::new(MockChain::default());
+    let h = build(&dir, chain.clone(), warn_only()).await;
+
+    let res = h
+        .submit(json!([
+            transfer("first", ALICE, "0.1"),
+            { "id": "swap", "kind": "swap", "network": "eth", "from_token": "FOO", "to_token": "ETH",
+              "amount": "5", "slippage": 0.5 },
+            transfer("last", BOB, "0.2"),
+        ]))
+        .await;
+    res.assert_status_ok();
+    let bundle: Value = res.json();
+    assert_eq!(bundle["status"], "failed");
+    assert_eq!(step(&bundle, "first")["status"], "completed");
+    assert_eq!(step(&bundle, "swap")["status"], "failed");
+    assert_eq!(step(&bundle, "swap")["error_code"], "UNSUPPORTED_TOKEN");
+    assert_eq!(step(&bundle, "last")["status"], "skipped");
+    assert_eq!(step(&bundle, "last")["error_code"], "BUNDLE_ABORTED");
+    assert_eq!(chain.broadcasts(), 1);
+
+    // continue：互不依赖的步骤照常执行
+    let res = h
+        .submit_request(json!({
+            "password": PASSWORD,
+            "failure_policy": "continue",
+            "steps": [
+                { "id": "swap", "kind": "swap", "network": "eth", "from_token": "FOO", "to_token": "ETH",
+                  "amount": "5", "slippage": 0.5 },
+                transfer("independent", BOB, "0.3"),
+            ],
+        }))
+        .await;
+    res.assert_status_ok();
+    let bundle: Value = res.json();
+    assert_eq!(bundle["status"], "partially_completed");
+    assert_eq!(step(&bundle, "independent")["status"], "completed");
+    assert_eq!(chain.broadcasts(), 2);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_limits_apply_to_the_bundle_total() {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = Arc::new(MockChain::default());
+    let h = build(&dir, chain.clone(), warn_only()).await;
+    h.storage.set_review_threshold(WALLET, Some("1"), "admin").await.unwrap();
+
+    // 每步都低于阈值，合计超过
+    let res = h.submit(json!([transfer("a", ALICE, "0.6"), transfer("b", BOB, "0.6")])).await;
+    res.assert_status_forbidden();
+    assert_eq!(res.json::<Value>()["error"]["code"], "BUNDLE_REQUIRES_REVIEW");
+    assert_eq!(chain.broadcasts(), 0);
+
+    let res = h.submit(json!([transfer("a", ALICE, "0.6")])).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["status"], "completed");
+
+    // 占位符只能引用已声明依赖的产出
+    for steps in [
+        json!([transfer("a", ALICE, "{{steps.b.amount}}"), transfer("b", BOB, "0.1")]),
+        json!([
+            transfer("a", ALICE, "0.1"),
+            { "id": "b", "kind": "transfer", "depends_on": ["c"], "network": "eth", "to": BOB, "amount": "0.1" },
+        ]),
+        json!([transfer("a", ALICE, "0.1"), transfer("a", BOB, "0.1")]),
+    ] {
+        let res = h.submit(steps).await;
+        res.assert_status_bad_request();
+        assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_BUNDLE");
+    }
+
+    let mut detector = AnomalyDetector::new();
+    detector.add_to_blacklist(format!("{:?}", BOB.parse::<ethers::types::Address>().unwrap()), "test".to_string());
+    let other = tempfile::tempdir().unwrap();
+    let blocked = build(&other, Arc::new(MockChain::default()), detector).await;
+    let res = blocked.submit(json!([transfer("a", ALICE, "0.1"), transfer("b", BOB, "0.1")])).await;
+    res.assert_status_forbidden();
+    assert_eq!(res.json::<Value>()["error"]["code"], "BUNDLE_BLOCKED");
+
+    // 运行时关闭该wallet的异常拦截：不重启即只告警
+    blocked
+        .app
+        .put("/api/admin/flags")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "flag": "anomaly_blocking", "wallet_id": WALLET, "enabled": false }))
+        .await
+        .assert_status_ok();
+    let res = blocked.submit(json!([transfer("a", ALICE, "0.1"), transfer("b", BOB, "0.1")])).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["status"], "completed");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_interrupted_bundle_resumes_without_repeating_steps() {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = Arc::new(MockChain { hang_on: Some(2), ..Default::default() });
+    let h = build(&dir, chain.clone(), warn_only()).await;
+
+    // 第二步卡在 prepare 时丢弃请求，相当于进程在执行中途退出
+    let steps = json!([transfer("a", ALICE, "0.1"), transfer("b", BOB, "0.2")]);
+    let request = h.submit_request(json!({ "password": PASSWORD, "steps": steps })).into_future();
+    tokio::select! {
+        _ = request => panic!("the second step should never finish"),
+        _ = chain.reached.notified() => {}
+    }
+    assert_eq!(chain.broadcasts(), 1);
+    drop(h);
+
+    let restarted = Arc::new(MockChain::starting_at(1));
+    let h = build(&dir, restarted.clone(), warn_only()).await;
+    let interrupted = h.storage.interrupt_running_bundles().await.unwrap();
+    assert_eq!(interrupted.len(), 1);
+    let id = interrupted[0].as_str();
+    let before = h.get(id).await;
+    assert_eq!(before["status"], "interrupted");
+    assert_eq!(step(&before, "a")["status"], "completed");
+    assert_eq!(step(&before, "b")["status"], "running");
+
+    let res = h.resume(id).await;
+    res.assert_status_ok();
+    let after: Value = res.json();
+    assert_eq!(after["status"], "completed");
+    assert_eq!(step(&after, "a")["output"], step(&before, "a")["output"]);
+    assert_eq!(step(&after, "b")["status"], "completed");
+    assert_eq!(restarted.broadcasts(), 1);
+
+    let res = h.resume(id).await;
+    res.assert_status(axum::http::StatusCode::CONFLICT);
+    assert_eq!(res.json::<Value>()["error"]["code"], "BUNDLE_NOT_RUNNABLE");
+}
@@ -25,7 +25,7 @@ async fn test_backup_flow_basic() {
     cfg.storage.database_url = "sqlite::memory:".to_string();
     let manager = WalletManager::new(&cfg).await.unwrap();
 
-    manager.create_wallet("b_test", true).await.unwrap();
+    manager.create_wallet("b_test", "test_password", true).await.unwrap();
     let res = manager.backup_wallet("b_test").await;
     
     // backup_wallet may succeed or fail depending on implementation completeness
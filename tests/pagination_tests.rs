@@ -0,0 +1,287 @@
+//! 统一分页：签名游标的往返与防篡改、插入期间的稳定遍历、各端点的 limit 上限，以及旧参数与游标分页结果一致
+
+use std::collections::HashSet;
+
+use axum_test::TestServer;
+use base64::Engine;
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::handlers::admin::AuditLogPages;
+use defi_hot_wallet::api::handlers::bridge::BridgeHistoryPages;
+use defi_hot_wallet::api::handlers::events::EventPages;
+use defi_hot_wallet::api::handlers::transaction::HistoryPages;
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::api::types::{PageQuery, PageRequest, PageScope};
+use defi_hot_wallet::api::validators::Validate;
+use defi_hot_wallet::blockchain::bridge::{BridgeTransaction, BridgeTransactionStatus};
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::core::wallet_manager::WalletManager;
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{journal_events, NewJournalEvent, TransactionRecord, WalletStorage};
+
+const API_KEY: &str = "pagination-test-key-0123456789abcdef";
+
+struct Fixture {
+    app: TestServer,
+    storage: std::sync::Arc<WalletStorage>,
+    manager: std::sync::Arc<WalletManager>,
+}
+
+async fn fixture() -> Fixture {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    let (storage, manager) = (server.storage.clone(), server.wallet_manager.clone());
+    Fixture { app: TestServer::new(server.create_router().await).unwrap(), storage, manager }
+}
+
+impl Fixture {
+    async fn get(&self, path: &str) -> Value {
+        let res = self.app.get(path).add_header("X-API-KEY", API_KEY).await;
+        res.assert_status_ok();
+        res.json()
+    }
+
+    async fn bridge(&self, id: &str, minute: i64) {
+        let at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minute);
+        self.storage
+            .store_bridge_transaction(&BridgeTransaction {
+                id: id.to_string(),
+                from_wallet: "alpha".to_string(),
+                from_chain: "eth".to_string(),
+                to_chain: "polygon".to_string(),
+                token: "USDC".to_string(),
+                amount: "10".to_string(),
+                status: BridgeTransactionStatus::Initiated,
+                source_tx_hash: None,
+                destination_tx_hash: None,
+                created_at: at,
+                updated_at: at,
+                fee_amount: None,
+                estimated_completion_time: None,
+                amount_minimal_source: None,
+                amount_minimal_dest: None,
+                token_address_source: None,
+                token_address_dest: None,
+            })
+            .await
+            .unwrap();
+    }
+
+    async fn events(&self, n: usize) {
+        for i in 0..n {
+            self.storage
+                .record_event(&NewJournalEvent {
+                    event_type: journal_events::LIMITS_CHANGED,
+                    entity_type: "wallet",
+                    entity_id: &format!("w{}", i),
+                    payload: json!({ "i": i }),
+                })
+                .await
+                .unwrap();
+        }
+    }
+}
+
+fn ids(body: &Value) -> Vec<String> {
+    body["items"].as_array().unwrap().iter().map(|t| t["id"].as_str().unwrap().to_string()).collect()
+}
+
+fn seqs(items: &Value) -> Vec<i64> {
+    items.as_array().unwrap().iter().map(|e| e["seq"].as_i64().unwrap()).collect()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_cursor_round_trips_and_rejects_tampering() {
+    let f = fixture().await;
+    f.events(5).await;
+
+    let first = f.get("/api/events?limit=2").await;
+    assert_eq!(seqs(&first["items"]), [1, 2]);
+    let cursor = first["next_cursor"].as_str().unwrap().to_string();
+    let second = f.get(&format!("/api/events?limit=2&cursor={}", cursor)).await;
+    assert_eq!(seqs(&second["items"]), [3, 4]);
+    // 同一游标可重放
+    assert_eq!(f.get(&format!("/api/events?limit=2&cursor={}", cursor)).await, second);
+
+    let (payload, tag) = cursor.split_once('.').unwrap();
+    let engine = &base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    let forged = engine.encode(br#"{"d":"asc","k":["0"]}"#);
+    let mut flipped = engine.decode(tag).unwrap();
+    flipped[0] ^= 1;
+    f.bridge("b1", 1).await;
+    f.bridge("b2", 2).await;
+    let bridge_cursor = f.get("/api/bridge/history?limit=1").await["next_cursor"].as_str().unwrap().to_string();
+
+    for bad in [
+        format!("{}.{}", forged, tag),
+        format!("{}.{}", payload, engine.encode(flipped)),
+        payload.to_string(),
+        "not-a-cursor".to_string(),
+        // 桥接历史的游标不能用于事件流
+        bridge_cursor,
+    ] {
+        let res = f.app.get(&format!("/api/events?cursor={}", bad)).add_header("X-API-KEY", API_KEY).await;
+        res.assert_status_bad_request();
+        assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_CURSOR", "{}", bad);
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_keyset_iteration_is_stable_under_inserts() {
+    let f = fixture().await;
+    // b3/b4 同一时刻创建，靠 id 决定先后
+    for (id, minute) in [("b1", 1), ("b2", 2), ("b3", 3), ("b4", 3), ("b5", 5), ("b6", 6), ("b7", 7)] {
+        f.bridge(id, minute).await;
+    }
+
+    let mut seen = Vec::new();
+    let mut path = "/api/bridge/history?limit=3".to_string();
+    let mut inserted = 0;
+    loop {
+        let body = f.get(&path).await;
+        seen.extend(ids(&body));
+        assert_eq!(body["total"], 7 + inserted);
+        // 翻页期间不断有更新的记录插入到列表头部
+        f.bridge(&format!("new-{}", inserted), 100 + inserted).await;
+        inserted += 1;
+        match body["next_cursor"].as_str() {
+            Some(c) => path = format!("/api/bridge/history?limit=3&cursor={}", c),
+            None => break,
+        }
+    }
+    assert_eq!(seen, ["b7", "b6", "b5", "b4", "b3", "b2", "b1"]);
+    assert_eq!(seen.iter().collect::<HashSet<_>>().len(), seen.len());
+
+    // 事件流升序：新事件只会追加到末尾
+    f.events(3).await;
+    let first = f.get("/api/events?limit=2").await;
+    f.events(2).await;
+    let rest = f.get(&format!("/api/events?cursor={}", first["next_cursor"].as_str().unwrap())).await;
+    assert_eq!([seqs(&first["items"]), seqs(&rest["items"])].concat(), [1, 2, 3, 4, 5]);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_legacy_parameters_produce_identical_pages() {
+    let f = fixture().await;
+    for i in 0..7 {
+        f.bridge(&format!("b{}", i), i).await;
+    }
+
+    let first = f.get("/api/bridge/history?limit=3").await;
+    let second = f.get(&format!("/api/bridge/history?limit=3&cursor={}", first["next_cursor"].as_str().unwrap())).await;
+    let legacy = f.get("/api/bridge/history?page=2&page_size=3").await;
+    assert_eq!(ids(&legacy), ids(&second));
+    assert_eq!((&legacy["page"], &legacy["page_size"], &legacy["total"]), (&json!(2), &json!(3), &json!(7)));
+    assert!(second.get("page").is_none());
+    // 旧参数返回的游标可直接续接新接口
+    let third = f.get(&format!("/api/bridge/history?limit=3&cursor={}", legacy["next_cursor"].as_str().unwrap())).await;
+    assert_eq!(ids(&third), ["b0"]);
+    assert!(third["next_cursor"].is_null());
+    assert!(f.get("/api/bridge/history?page=3&page_size=3").await["next_cursor"].is_null());
+
+    f.events(5).await;
+    let legacy = f.get("/api/events?after_seq=2&limit=2").await;
+    assert_eq!(seqs(&legacy["events"]), [3, 4]);
+    assert_eq!(legacy["next_seq"], 4);
+    let first = f.get("/api/events?limit=2").await;
+    let page = f.get(&format!("/api/events?limit=2&cursor={}", first["next_cursor"].as_str().unwrap())).await;
+    assert_eq!(page["items"], legacy["events"]);
+}
+
+#[test]
+fn test_limits_are_capped_per_endpoint() {
+    fn limit<S: PageScope>(limit: Option<i64>) -> usize {
+        PageRequest::<S>::validate(PageQuery { cursor: None, limit }).unwrap().limit
+    }
+    assert_eq!(limit::<BridgeHistoryPages>(Some(10_000)), 100);
+    assert_eq!(limit::<EventPages>(Some(10_000)), 1000);
+    assert_eq!(limit::<AuditLogPages>(Some(10_000)), 500);
+    assert_eq!(limit::<HistoryPages>(Some(10_000)), 200);
+    assert_eq!(limit::<BridgeHistoryPages>(Some(0)), 1);
+    assert_eq!(limit::<EventPages>(Some(-5)), 1);
+    assert_eq!(limit::<BridgeHistoryPages>(None), BridgeHistoryPages::DEFAULT_LIMIT);
+    assert!(!PageRequest::<EventPages>::validate(PageQuery::default()).unwrap().explicit);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_audit_logs_and_history_pages() {
+    let f = fixture().await;
+    for i in 0..5 {
+        f.storage.log_action("alpha", &format!("action-{}", i), "{}", None, None).await.unwrap();
+    }
+    f.storage.log_action("beta", "other", "{}", None, None).await.unwrap();
+    f.app.get("/api/admin/audit-logs").await.assert_status_unauthorized();
+
+    let mut actions = Vec::new();
+    let mut path = "/api/admin/audit-logs?wallet_id=alpha&limit=2".to_string();
+    loop {
+        let body = f.get(&path).await;
+        assert!(body["items"].as_array().unwrap().len() <= 2);
+        actions.extend(body["items"].as_array().unwrap().iter().map(|l| l["action"].as_str().unwrap().to_string()));
+        match body["next_cursor"].as_str() {
+            Some(c) => path = format!("/api/admin/audit-logs?wallet_id=alpha&limit=2&cursor={}", c),
+            None => break,
+        }
+    }
+    assert_eq!(actions, ["action-4", "action-3", "action-2", "action-1", "action-0"]);
+
+    // 不带分页参数的旧响应需要wallet已加载；记录需要wallet已落库
+    f.manager.create_wallet("alpha", "Pag1nation-Wallet", false).await.unwrap();
+    f.storage.store_wallet("alpha", b"blob", false).await.unwrap();
+    let wallet_id = f.storage.wallet_id("alpha").await.unwrap().unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    for i in 0..3 {
+        f.storage
+            .store_transaction(&TransactionRecord {
+                id: format!("tx-{}", i),
+                wallet_id: wallet_id.clone(),
+                tx_hash: format!("0x{:064x}", i),
+                network: "eth".to_string(),
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: "0x2222222222222222222222222222222222222222".to_string(),
+                amount: "1".to_string(),
+                fee: "0.001".to_string(),
+                status: "confirmed".to_string(),
+                created_at: start + Duration::minutes(i),
+                confirmed_at: None,
+                integrity_hash: String::new(),
+                fee_actual: None,
+                block_number: None,
+            })
+            .await
+            .unwrap();
+    }
+    let first = f.get("/api/transactions/history?wallet_name=alpha&limit=2").await;
+    let hashes = |body: &Value| -> Vec<String> {
+        body["items"].as_array().unwrap().iter().map(|t| t["hash"].as_str().unwrap().to_string()).collect()
+    };
+    assert_eq!(hashes(&first), [format!("0x{:064x}", 2), format!("0x{:064x}", 1)]);
+    let cursor = first["next_cursor"].as_str().unwrap();
+    let rest = f.get(&format!("/api/transactions/history?wallet_name=alpha&limit=2&cursor={}", cursor)).await;
+    assert_eq!(hashes(&rest), [format!("0x{:064x}", 0)]);
+    assert!(rest["next_cursor"].is_null());
+    // 不带分页参数仍是旧的全量响应
+    assert_eq!(f.get("/api/transactions/history?wallet_name=alpha").await["transactions"].as_array().unwrap().len(), 3);
+}
@@ -0,0 +1,196 @@
+//! EIP-681 付款链接（`/api/parse/payment_uri`、`/api/wallets/:name/payment_uri`、send 的 `payment_uri`）集成测试
+
+mod util;
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+
+
+const API_KEY: &str = "payment-uri-admin-key";
+const SESSION: &str = "payment-uri-session";
+const ADDRESS: &str = "0x742d35cc6634c0532925a3b844bc454e4438f44e";
+const CHECKSUMMED: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+const TOKEN: &str = "0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7";
+
+struct Harness {
+    app: TestServer,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY)).await;
+
+    let user_id = util::sign_in(&server, "merchants@example.com", SESSION).await;
+    server.user_db.link_wallet(&user_id, "shop", ADDRESS, None).await.unwrap();
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { app, _dir: dir }
+}
+
+async fn decode(h: &Harness, body: Value) -> axum_test::TestResponse {
+    h.app
+        .post("/api/parse/payment_uri")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&body)
+        .await
+}
+
+async fn send(h: &Harness, query: &str, body: Value) -> axum_test::TestResponse {
+    h.app
+        .post(&format!("/api/wallets/shop/send{}", query))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&body)
+        .await
+}
+
+fn send_error_code(res: &axum_test::TestResponse) -> String {
+    let body: Value = res.json();
+    body["error"]["code"].as_str().unwrap_or_default().to_string()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_decode_native_and_erc20() {
+    let h = build().await;
+
+    let res = decode(&h, json!({ "uri": format!("ethereum:{}@1?value=2.014e18", ADDRESS) })).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["kind"], "native");
+    assert_eq!(body["recipient"], CHECKSUMMED);
+    assert_eq!(body["network"], "eth");
+    assert_eq!(body["symbol"], "ETH");
+    assert_eq!(body["amount_minimal"], "2014000000000000000");
+    assert_eq!(body["amount"], "2.014");
+    assert_eq!(body["uri"], format!("ethereum:{}@1?value=2014000000000000000", CHECKSUMMED));
+
+    // 无 chain id：network留给付款方
+    let body: Value = decode(&h, json!({ "uri": format!("ethereum:pay-{}?value=1e15", ADDRESS) })).await.json();
+    assert!(body["network"].is_null());
+    assert_eq!(body["amount"], "0.001");
+
+    let uri = format!("ethereum:{}@11155111/transfer?address={}&uint256=2.5e6", TOKEN, ADDRESS);
+    let body: Value = decode(&h, json!({ "uri": uri, "token_decimals": 6 })).await.json();
+    assert_eq!(body["kind"], "erc20");
+    assert_eq!(body["network"], "sepolia");
+    assert_eq!(body["recipient"], CHECKSUMMED);
+    assert_eq!(body["token"].as_str().unwrap().to_lowercase(), TOKEN);
+    assert_eq!(body["amount_minimal"], "2500000");
+    assert_eq!(body["amount"], "2.5");
+
+    // 不知道小数位时只给最小单位
+    let body: Value = decode(&h, json!({ "uri": uri })).await.json();
+    assert!(body["amount"].is_null());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_decode_rejections() {
+    let h = build().await;
+
+    // chain id 137 未配置
+    let res = decode(&h, json!({ "uri": format!("ethereum:{}@137?value=1", ADDRESS) })).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "UNKNOWN_CHAIN_ID");
+
+    for uri in [
+        format!("ethereum:{}?value=1.5e0", ADDRESS),
+        "ethereum:merchant.eth?value=1".to_string(),
+        format!("bitcoin:{}", ADDRESS),
+        format!("ethereum:{}/approve?address={}", TOKEN, ADDRESS),
+    ] {
+        let res = decode(&h, json!({ "uri": uri })).await;
+        res.assert_status_bad_request();
+        assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_PAYMENT_URI", "{}", uri);
+    }
+
+    let res = h
+        .app
+        .post("/api/parse/payment_uri")
+        .json(&json!({ "uri": format!("ethereum:{}", ADDRESS) }))
+        .await;
+    res.assert_status_unauthorized();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_generated_uri_round_trips() {
+    let h = build().await;
+
+    let res = h
+        .app
+        .get("/api/wallets/shop/payment_uri?network=sepolia&amount=1.5")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await;
+    res.assert_status_ok();
+    let generated: Value = res.json();
+    assert_eq!(generated["uri"], format!("ethereum:{}@11155111?value=1500000000000000000", CHECKSUMMED));
+    assert_eq!(generated["amount"], "1.5");
+
+    let parsed: Value = decode(&h, json!({ "uri": generated["uri"] })).await.json();
+    assert_eq!(parsed, generated);
+
+    // 不带金额时由付款方填写
+    let generated: Value = h
+        .app
+        .get("/api/wallets/shop/payment_uri")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await
+        .json();
+    assert_eq!(generated["uri"], format!("ethereum:{}@1", CHECKSUMMED));
+    assert!(generated["amount_minimal"].is_null());
+
+    let res = h
+        .app
+        .get("/api/wallets/shop/payment_uri?network=btc")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await;
+    res.assert_status_bad_request();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_send_payment_uri_must_agree() {
+    let h = build().await;
+    let uri = format!("ethereum:{}@1?value=1e17", ADDRESS);
+    let other = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+    let cases = [
+        ("", json!({ "payment_uri": uri, "to": other, "password": "x" }), "PAYMENT_URI_MISMATCH"),
+        ("", json!({ "payment_uri": uri, "amount": "0.2", "password": "x" }), "PAYMENT_URI_MISMATCH"),
+        ("", json!({ "payment_uri": uri, "network": "sepolia", "password": "x" }), "PAYMENT_URI_MISMATCH"),
+        ("?network=sepolia", json!({ "payment_uri": uri, "password": "x" }), "PAYMENT_URI_MISMATCH"),
+        (
+            "",
+            json!({ "payment_uri": format!("ethereum:{}@137?value=1", ADDRESS), "password": "x" }),
+            "UNKNOWN_CHAIN_ID",
+        ),
+        ("", json!({ "payment_uri": format!("ethereum:{}@1", ADDRESS), "password": "x" }), "MISSING_PARAMETER"),
+        (
+            "",
+            json!({
+                "payment_uri": format!("ethereum:{}@1/transfer?address={}&uint256=1", TOKEN, ADDRESS),
+                "password": "x",
+            }),
+            "UNSUPPORTED_PAYMENT_URI",
+        ),
+        ("", json!({ "payment_uri": "ethereum:shop.eth?value=1", "password": "x" }), "INVALID_PAYMENT_URI"),
+    ];
+    for (query, body, code) in cases {
+        let res = send(&h, query, body.clone()).await;
+        res.assert_status_bad_request();
+        assert_eq!(send_error_code(&res), code, "{}", body);
+    }
+
+    // 一致的 to / amount / network 可以与链接同时给出（大小写与记法不同也算一致）
+    let agreeing = json!({
+        "payment_uri": uri,
+        "to": ADDRESS,
+        "amount": "0.1",
+        "network": "eth",
+        "password": "x",
+    });
+    let res = send(&h, "", agreeing).await;
+    assert_ne!(send_error_code(&res), "PAYMENT_URI_MISMATCH");
+}
@@ -0,0 +1,242 @@
+//! PostgreSQL 存储后端：需要 `--features postgres`，并把 `TEST_POSTGRES_URL`
+//! 指向一个测试用的 PostgreSQL 实例；未设置时这些测试直接跳过
+#![cfg(feature = "postgres")]
+
+use chrono::Utc;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::Executor;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::bridge::{BridgeTransaction, BridgeTransactionStatus};
+use defi_hot_wallet::core::config::WalletConfig;
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::storage::{open_storage, PostgresWalletStorage, TransactionRecord, WalletStorageTrait};
+
+const TEST_KEY: &str = "MTIzNDU2Nzg5MDEyMzQ1Njc4OTAxMjM0NTY3ODkwMTI=";
+
+/// 每个测试用自己的 schema：审计链覆盖整张表，一个测试篡改的记录不能影响其他测试
+async fn connect() -> Option<PostgresWalletStorage> {
+    let Ok(url) = std::env::var("TEST_POSTGRES_URL") else {
+        eprintln!("TEST_POSTGRES_URL not set; skipping");
+        return None;
+    };
+    std::env::set_var("WALLET_ENC_KEY", TEST_KEY);
+    let schema = format!("t_{}", uuid::Uuid::new_v4().simple());
+    let admin = PgPool::connect(&url).await.unwrap();
+    sqlx::query(&format!("CREATE SCHEMA {}", schema)).execute(&admin).await.unwrap();
+    let pool = PgPoolOptions::new()
+        .after_connect(move |conn, _| {
+            let set_path = format!("SET search_path TO {}", schema);
+            Box::pin(async move {
+                conn.execute(set_path.as_str()).await?;
+                Ok(())
+            })
+        })
+        .connect(&url)
+        .await
+        .unwrap();
+    Some(PostgresWalletStorage::with_pool(pool).await.unwrap())
+}
+
+/// 测试共用一个数据库，名字各自唯一
+fn unique(prefix: &str) -> String {
+    format!("{}-{}", prefix, uuid::Uuid::new_v4())
+}
+
+#[tokio::test]
+async fn test_wallet_roundtrip_is_audited() {
+    let Some(storage) = connect().await else { return };
+    let name = unique("pg-wallet");
+
+    storage.store_wallet(&name, b"sealed", false).await.unwrap();
+    assert!(storage.store_wallet(&name, b"again", false).await.is_err(), "names are unique");
+    assert_eq!(storage.load_wallet(&name).await.unwrap(), (b"sealed".to_vec(), false));
+    storage.update_wallet_encrypted_data(&name, b"resealed").await.unwrap();
+    assert_eq!(storage.load_wallet(&name).await.unwrap().0, b"resealed".to_vec());
+    let id = storage.list_wallets().await.unwrap().into_iter().find(|w| w.name == name).unwrap().id;
+
+    // 每条审计记录都通过 HMAC 校验
+    let actions: Vec<_> = storage.get_audit_logs(Some(&id)).await.unwrap().into_iter().map(|l| l.action).collect();
+    assert_eq!(actions.iter().filter(|a| *a == "wallet_accessed").count(), 2);
+    assert!(actions.contains(&"wallet_created".to_string()));
+
+    storage.delete_wallet(&name).await.unwrap();
+    assert!(storage.load_wallet(&name).await.is_err());
+    assert!(storage.delete_wallet(&name).await.is_err());
+    assert!(storage.update_wallet_encrypted_data(&name, b"x").await.is_err());
+
+    // 改动审计内容后校验失败
+    sqlx::query("UPDATE audit_logs SET details = 'edited' WHERE wallet_id = $1 AND action = 'wallet_deleted'")
+        .bind(&id)
+        .execute(storage.pool())
+        .await
+        .unwrap();
+    assert!(storage.get_audit_logs(Some(&id)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_deleting_an_audit_row_breaks_the_chain() {
+    let Some(storage) = connect().await else { return };
+    for action in ["first", "second", "third"] {
+        storage.log_action("pg-chain", action, "details", None, None).await.unwrap();
+    }
+    let report = storage.verify_audit_chain().await.unwrap();
+    assert!(report.is_intact(), "{:?}", report);
+    assert_eq!(report.verified, 3);
+
+    // 连同 MAC 一起删掉中间一条：各行自身的 MAC 仍然正确，链接断开
+    let middle: i64 = sqlx::query_scalar("SELECT id FROM audit_logs WHERE action = 'second'")
+        .fetch_one(storage.pool())
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM audit_logs_hmac WHERE audit_id = $1").bind(middle).execute(storage.pool()).await.unwrap();
+    sqlx::query("DELETE FROM audit_logs WHERE id = $1").bind(middle).execute(storage.pool()).await.unwrap();
+
+    let report = storage.verify_audit_chain().await.unwrap();
+    assert_eq!(report.verified, 1);
+    assert_eq!(report.reason.as_deref(), Some("does not link to the previous entry"));
+    let err = storage.get_audit_logs(Some("pg-chain")).await.unwrap_err();
+    assert!(err.to_string().contains("chain broken"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_transaction_integrity_hash() {
+    let Some(storage) = connect().await else { return };
+    let name = unique("pg-tx");
+    storage.store_wallet(&name, b"sealed", false).await.unwrap();
+    let wallet = storage.list_wallets().await.unwrap().into_iter().find(|w| w.name == name).unwrap().id;
+    let record = TransactionRecord {
+        id: unique("tx"),
+        wallet_id: wallet.clone(),
+        tx_hash: format!("0x{}", "ab".repeat(32)),
+        network: "eth".to_string(),
+        from_address: "0x0000000000000000000000000000000000000001".to_string(),
+        to_address: "0x0000000000000000000000000000000000000002".to_string(),
+        amount: "1.5".to_string(),
+        fee: "0.001".to_string(),
+        status: "pending".to_string(),
+        created_at: Utc::now(),
+        confirmed_at: None,
+        integrity_hash: String::new(),
+        fee_actual: None,
+        block_number: None,
+    };
+    storage.store_transaction(&record).await.unwrap();
+    let stored = storage.get_wallet_transactions(&wallet).await.unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].amount, "1.5");
+
+    // 回执字段随记录写入，读回后 integrity hash 照常通过
+    let mined = TransactionRecord {
+        id: unique("tx"),
+        status: "confirmed".to_string(),
+        confirmed_at: Some(Utc::now()),
+        fee_actual: Some("0.000042".to_string()),
+        block_number: Some(17_000_000),
+        ..record.clone()
+    };
+    storage.store_transaction(&mined).await.unwrap();
+    let stored = storage.get_wallet_transactions(&wallet).await.unwrap();
+    let stored = stored.iter().find(|tx| tx.id == mined.id).unwrap();
+    assert_eq!((stored.fee_actual.as_deref(), stored.block_number), (Some("0.000042"), Some(17_000_000)));
+
+    sqlx::query("UPDATE transactions SET amount = '150' WHERE id = $1")
+        .bind(&record.id)
+        .execute(storage.pool())
+        .await
+        .unwrap();
+    let err = storage.get_wallet_transactions(&wallet).await.unwrap_err();
+    assert!(err.to_string().contains("integrity"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_concurrent_nonce_reservations_are_distinct() {
+    let Some(storage) = connect().await else { return };
+    let storage = Arc::new(storage);
+    let address = unique("0xpg");
+
+    let tasks: Vec<_> = (0..32)
+        .map(|_| {
+            let storage = storage.clone();
+            let address = address.clone();
+            tokio::spawn(async move { storage.reserve_next_nonce("eth", &address, 7).await.unwrap() })
+        })
+        .collect();
+    let mut nonces = HashSet::new();
+    for task in tasks {
+        assert!(nonces.insert(task.await.unwrap()), "nonce handed out twice");
+    }
+    assert_eq!(nonces, (7..39).collect::<HashSet<u64>>());
+
+    // 区间从存储值与 floor 中较大的一个开始
+    assert_eq!(storage.reserve_nonce_range("eth", &address, 0, 3).await.unwrap(), 39);
+    assert_eq!(storage.reserve_nonce_range("eth", &address, 100, 2).await.unwrap(), 100);
+    storage.mark_nonce_used("eth", &address, 50).await.unwrap();
+    storage.mark_nonce_used("eth", &address, 200).await.unwrap();
+    assert_eq!(storage.next_nonce("eth", &address).await.unwrap(), Some(201));
+    assert!(storage.reserve_nonce_range("eth", &address, 0, 0).await.is_err());
+}
+
+#[tokio::test]
+async fn test_bridge_transactions_through_the_factory() {
+    let Ok(url) = std::env::var("TEST_POSTGRES_URL") else { return };
+    std::env::set_var("WALLET_ENC_KEY", TEST_KEY);
+    let storage = open_storage(&url).await.unwrap();
+    assert!(storage.as_any().downcast_ref::<PostgresWalletStorage>().is_some());
+
+    let now = Utc::now();
+    let tx = BridgeTransaction {
+        id: unique("bridge"),
+        from_wallet: "treasury".to_string(),
+        from_chain: "eth".to_string(),
+        to_chain: "bsc".to_string(),
+        token: "USDC".to_string(),
+        amount: "25".to_string(),
+        status: BridgeTransactionStatus::Initiated,
+        source_tx_hash: None,
+        destination_tx_hash: None,
+        created_at: now,
+        updated_at: now,
+        fee_amount: Some("0.1".to_string()),
+        estimated_completion_time: None,
+        amount_minimal_source: Some("25000000".to_string()),
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
+    };
+    storage.store_bridge_transaction(&tx).await.unwrap();
+    let failed = BridgeTransactionStatus::Failed("timeout".into());
+    storage.update_bridge_transaction_status(&tx.id, failed.clone(), Some("0xsrc".into())).await.unwrap();
+    let stored = storage.get_bridge_transaction(&tx.id).await.unwrap();
+    assert_eq!(stored.status, failed);
+    assert_eq!(stored.source_tx_hash.as_deref(), Some("0xsrc"));
+    assert_eq!(stored.amount_minimal_source.as_deref(), Some("25000000"));
+
+    // key rotation 记录
+    let label = unique("label");
+    storage.rotation_insert_version(&label, 1, "key-1").await.unwrap();
+    storage.rotation_upsert_label(&label, 1, Some("key-1")).await.unwrap();
+    storage.rotation_inc_usage(&label, 1).await.unwrap();
+    storage.rotation_mark_retired(&label, 1).await.unwrap();
+    let version = storage.rotation_get_version(&label, 1).await.unwrap().unwrap();
+    assert!(version.retired);
+    assert_eq!(version.usage_count, 1);
+    assert_eq!(storage.rotation_get_label(&label).await.unwrap().unwrap().current_id.as_deref(), Some("key-1"));
+}
+
+#[tokio::test]
+async fn test_server_refuses_a_postgres_database() {
+    let Ok(url) = std::env::var("TEST_POSTGRES_URL") else { return };
+    std::env::set_var("WALLET_ENC_KEY", TEST_KEY);
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let mut config = WalletConfig::default();
+    config.storage.database_url = url;
+
+    // 服务端的 journal、审批等表只在 SQLite 里；不能半途以 Postgres 启动
+    let err = WalletServer::new_for_test("127.0.0.1".to_string(), 0, config, None, None).await.err().unwrap();
+    assert!(matches!(err, WalletError::ConfigError(ref msg) if msg.contains("sqlite:")), "{}", err);
+}
@@ -0,0 +1,216 @@
+//! 启动前依赖检查：坏配置的具体失败项、退出码，以及全绿的一次运行
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use async_trait::async_trait;
+use base64::Engine as _;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use defi_hot_wallet::core::config::{
+    BlockchainConfig, NetworkConfig, PreflightConfig, StorageConfig, WalletConfig,
+};
+use defi_hot_wallet::ops::preflight::{
+    CheckStatus, Preflight, ProbeTransport, PREFLIGHT_FAILURE_EXIT_CODE,
+};
+
+/// rpc_url → 返回的 chain id；不在表里的 URL 视为不可达
+struct MockTransport {
+    chain_ids: HashMap<&'static str, u64>,
+    slow: Option<&'static str>,
+}
+
+#[async_trait]
+impl ProbeTransport for MockTransport {
+    async fn chain_id(&self, rpc_url: &str) -> Result<u64, String> {
+        if self.slow == Some(rpc_url) {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+        self.chain_ids
+            .get(rpc_url)
+            .copied()
+            .ok_or_else(|| "error sending request: connection refused".to_string())
+    }
+
+    async fn ping(&self, url: &str) -> Result<(), String> {
+        if url.contains("down") {
+            Err("connection refused".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn key(len: usize) -> String {
+    base64::engine::general_purpose::STANDARD.encode(vec![0x5au8; len])
+}
+
+fn network(name: &str, rpc_url: &str, chain_id: u64) -> NetworkConfig {
+    NetworkConfig {
+        name: name.to_string(),
+        rpc_url: rpc_url.to_string(),
+        chain_id,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
+    }
+}
+
+fn config(dir: &tempfile::TempDir, networks: Vec<(&str, NetworkConfig)>) -> WalletConfig {
+    WalletConfig {
+        storage: StorageConfig {
+            database_url: format!("sqlite://{}", dir.path().join("data/wallet.db").display()),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        blockchain: BlockchainConfig {
+            networks: networks.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+        },
+        ..Default::default()
+    }
+}
+
+fn transport() -> Arc<MockTransport> {
+    Arc::new(MockTransport {
+        chain_ids: HashMap::from([("http://eth.local", 1), ("http://polygon.local", 137)]),
+        slow: None,
+    })
+}
+
+#[tokio::test]
+async fn test_broken_config_reports_specific_failures() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(
+        &dir,
+        vec![
+            ("eth", network("Ethereum", "http://eth.local", 1)),
+            ("polygon", network("Polygon", "http://polygon.local", 80002)),
+            ("bsc", network("BSC", "http://unreachable.local", 56)),
+        ],
+    );
+    let report = Preflight::new(&config, &PreflightConfig::default())
+        .with_master_key(Some(key(31)))
+        .with_transport(transport())
+        .run()
+        .await;
+
+    assert!(!report.passed);
+    assert_eq!(report.exit_code(), PREFLIGHT_FAILURE_EXIT_CODE);
+
+    let master_key = report.check("master_key").unwrap();
+    assert_eq!(master_key.status, CheckStatus::Fail);
+    assert_eq!(master_key.detail, "WALLET_ENC_KEY decodes to 31 bytes — expected 32");
+    assert!(master_key.hint.is_some());
+
+    let bsc = report.check("rpc:bsc").unwrap();
+    assert_eq!(bsc.status, CheckStatus::Fail);
+    assert!(bsc.detail.contains("connection refused"), "{}", bsc.detail);
+    let polygon = report.check("rpc:polygon").unwrap();
+    assert_eq!(polygon.status, CheckStatus::Fail);
+    assert_eq!(polygon.detail, "chain_id 137 but expected 80002");
+    assert_eq!(report.check("rpc:eth").unwrap().status, CheckStatus::Pass);
+    assert_eq!(report.check("database").unwrap().status, CheckStatus::Pass);
+
+    let mut failed: Vec<&str> = report.failures().map(|c| c.name.as_str()).collect();
+    failed.sort();
+    assert_eq!(failed, vec!["master_key", "rpc:bsc", "rpc:polygon"]);
+
+    // 结构化 JSON 供 supervisor 采集
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json["passed"], false);
+    assert!(json["checks"].as_array().unwrap().iter().any(|c| c["name"] == "rpc:bsc" && c["status"] == "fail"));
+    let table = report.to_table();
+    assert!(table.contains("rpc:polygon"));
+    assert!(table.ends_with("preflight: FAILED\n"));
+}
+
+#[tokio::test]
+async fn test_fully_green_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(
+        &dir,
+        vec![
+            ("eth", network("Ethereum", "http://eth.local", 1)),
+            ("polygon", network("Polygon", "http://polygon.local", 137)),
+        ],
+    );
+    let settings = PreflightConfig {
+        writable_dirs: vec![dir.path().join("exports")],
+        integrations: BTreeMap::from([("webhooks".to_string(), "http://hooks.local/health".to_string())]),
+        ..Default::default()
+    };
+    let report = Preflight::new(&config, &settings)
+        .with_master_key(Some(key(32)))
+        .with_transport(transport())
+        .run()
+        .await;
+
+    assert!(report.passed, "{}", report.to_table());
+    assert_eq!(report.exit_code(), 0);
+    assert!(report.checks.iter().all(|c| c.status == CheckStatus::Pass), "{}", report.to_table());
+    // database, master_key, 2 × rpc, data dir + exports, webhooks
+    assert_eq!(report.checks.len(), 7);
+    assert!(report.check(&format!("dir:{}", dir.path().join("exports").display())).is_some());
+}
+
+#[tokio::test]
+async fn test_optional_integration_only_warns_and_slow_rpc_times_out() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(&dir, vec![("eth", network("Ethereum", "http://eth.local", 1))]);
+    let settings = PreflightConfig {
+        integrations: BTreeMap::from([("price_feed".to_string(), "http://down.local".to_string())]),
+        ..Default::default()
+    };
+    let report = Preflight::new(&config, &settings)
+        .with_master_key(Some(key(32)))
+        .with_transport(transport())
+        .run()
+        .await;
+    assert!(report.passed);
+    let feed = report.check("integration:price_feed").unwrap();
+    assert_eq!(feed.status, CheckStatus::Warn);
+    assert!(!feed.required);
+
+    let slow = Arc::new(MockTransport {
+        chain_ids: HashMap::from([("http://eth.local", 1)]),
+        slow: Some("http://eth.local"),
+    });
+    let report = Preflight::new(&config, &PreflightConfig::default())
+        .with_master_key(Some(key(32)))
+        .with_transport(slow)
+        .with_probe_timeout(Duration::from_millis(100))
+        .run()
+        .await;
+    let eth = report.check("rpc:eth").unwrap();
+    assert_eq!(eth.status, CheckStatus::Fail);
+    assert!(eth.detail.starts_with("timed out"), "{}", eth.detail);
+    assert_eq!(report.exit_code(), PREFLIGHT_FAILURE_EXIT_CODE);
+}
+
+#[test]
+fn test_server_check_exits_with_code_2() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "[blockchain.networks.eth]\nrpc_url = \"http://127.0.0.1:1\"\nchain_id = 1\n",
+    )
+    .unwrap();
+
+    let output = cargo_bin_cmd!("hot_wallet")
+        .args(["server", "--check"])
+        .env("CONFIG_PATH", &config_path)
+        .env("DATABASE_URL", format!("sqlite://{}", dir.path().join("wallets.db").display()))
+        .env("WALLET_ENC_KEY", key(31))
+        .env_remove("TEST_SKIP_DECRYPT")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(PREFLIGHT_FAILURE_EXIT_CODE));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("WALLET_ENC_KEY decodes to 31 bytes — expected 32"), "{}", stdout);
+    assert!(stdout.lines().any(|l| l.starts_with("rpc:eth") && l.contains("FAIL")), "{}", stdout);
+    assert!(stdout.contains("preflight: FAILED"));
+}
@@ -0,0 +1,229 @@
+//! 法币估值：CoinGecko 兼容价格源（mock HTTP）、缓存合并请求，以及余额 /
+//! 历史响应中的 `fiat_value` 与价格源故障时的降级
+
+mod util;
+
+use axum_test::TestServer;
+use chrono::Utc;
+use httpmock::{Method, MockServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::pricing::{CachedPriceFeed, CoinGeckoFeed, PriceError, PriceFeed};
+use defi_hot_wallet::storage::TransactionRecord;
+
+const API_KEY: &str = "pricing-test-admin-key";
+const SESSION: &str = "pricing-session-token";
+const WALLET: &str = "fiat_wallet";
+const ADDRESS: &str = "0x1234567890123456789012345678901234567890";
+
+fn mock_price<'a>(server: &'a MockServer, currency: &str, price: &str) -> httpmock::Mock<'a> {
+    let body = format!(r#"{{"ethereum":{{"{}":{},"last_updated_at":1700000000}}}}"#, currency, price);
+    server.mock(|when, then| {
+        when.method(Method::GET)
+            .path("/simple/price")
+            .query_param("ids", "ethereum")
+            .query_param("vs_currencies", currency);
+        then.status(200).header("content-type", "application/json").body(body);
+    })
+}
+
+#[tokio::test]
+async fn test_feed_reads_decimal_price_and_sends_api_key() {
+    let upstream = MockServer::start_async().await;
+    let mock = upstream.mock(|when, then| {
+        when.method(Method::GET)
+            .path("/simple/price")
+            .query_param("ids", "ethereum")
+            .query_param("vs_currencies", "eur")
+            .header("x-cg-pro-api-key", "cg-test-key");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"ethereum":{"eur":1843.27,"last_updated_at":1700000000}}"#);
+    });
+
+    let feed = CoinGeckoFeed::new(upstream.base_url()).with_api_key("x-cg-pro-api-key", "cg-test-key");
+    let price = feed.get_price("eth", "EUR").await.unwrap();
+    assert_eq!(price.price.to_string(), "1843.27");
+    assert_eq!(price.as_of.timestamp(), 1_700_000_000);
+    mock.assert();
+}
+
+#[tokio::test]
+async fn test_feed_errors() {
+    let upstream = MockServer::start_async().await;
+    upstream.mock(|when, then| {
+        when.method(Method::GET).path("/simple/price").query_param("vs_currencies", "usd");
+        then.status(500).body("upstream exploded");
+    });
+    upstream.mock(|when, then| {
+        when.method(Method::GET).path("/simple/price").query_param("vs_currencies", "xyz");
+        then.status(200).header("content-type", "application/json").body(r#"{"ethereum":{}}"#);
+    });
+    let feed = CoinGeckoFeed::new(upstream.base_url());
+
+    assert_eq!(feed.get_price("ETH", "usd").await, Err(PriceError::Status(500)));
+    assert!(matches!(feed.get_price("ETH", "xyz").await, Err(PriceError::NotQuoted { .. })));
+    assert_eq!(feed.get_price("NOPE", "usd").await, Err(PriceError::UnknownSymbol("NOPE".to_string())));
+    // 配置补充的映射
+    let feed = feed.with_coin_id("nope", "ethereum");
+    assert_eq!(feed.get_price("NOPE", "usd").await, Err(PriceError::Status(500)));
+}
+
+#[tokio::test]
+async fn test_cache_coalesces_concurrent_requests() {
+    let upstream = MockServer::start_async().await;
+    let mock = upstream.mock(|when, then| {
+        when.method(Method::GET).path("/simple/price");
+        then.status(200)
+            .header("content-type", "application/json")
+            .delay(Duration::from_millis(200))
+            .body(r#"{"ethereum":{"usd":2000.5,"last_updated_at":1700000000}}"#);
+    });
+    let feed = CachedPriceFeed::new(
+        Arc::new(CoinGeckoFeed::new(upstream.base_url())),
+        Duration::from_secs(60),
+        Duration::from_secs(600),
+    );
+
+    let prices = futures::future::join_all((0..10).map(|_| feed.get_price("ETH", "usd"))).await;
+    assert!(prices.iter().all(|p| p.as_ref().unwrap().price.to_string() == "2000.5"));
+    assert_eq!(mock.hits(), 1);
+
+    // 新鲜期内不再请求上游
+    feed.get_price("eth", "USD").await.unwrap();
+    assert_eq!(mock.hits(), 1);
+}
+
+struct Harness {
+    app: TestServer,
+    server: WalletServer,
+    _dir: tempfile::TempDir,
+}
+
+async fn build(upstream: &MockServer) -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USE_TEST_BALANCE", "true");
+    let feed = CachedPriceFeed::new(
+        Arc::new(CoinGeckoFeed::new(upstream.base_url())),
+        Duration::from_secs(60),
+        Duration::from_secs(600),
+    );
+    let server =
+        util::test_server(dir.path(), util::memory_config(), Some(API_KEY)).await.with_price_feed(Arc::new(feed));
+
+    let pool = server.user_db.pool();
+    for migration in [
+        include_str!("../migrations/004_create_user_preferences.sql"),
+        include_str!("../migrations/007_add_preferred_currency.sql"),
+    ] {
+        sqlx::query(migration).execute(pool).await.unwrap();
+    }
+    let user_id = util::sign_in(&server, "fiat@example.com", SESSION).await;
+    server.user_db.link_wallet(&user_id, WALLET, ADDRESS, None).await.unwrap();
+    sqlx::query(
+        "INSERT INTO user_preferences (user_id, preferred_currency, updated_at, created_at) VALUES (?1, 'eur', 0, 0)",
+    )
+    .bind(&user_id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    // 历史接口只认 WalletManager 中的wallet
+    let wallet_id = util::create_wallet(&server, WALLET, "fiat-wallet-pass").await;
+    let created_at = Utc::now() - chrono::Duration::hours(1);
+    server
+        .storage
+        .store_transaction(&TransactionRecord {
+            id: "fiat-tx-1".to_string(),
+            wallet_id,
+            tx_hash: format!("0x{:064x}", 1),
+            network: "eth".to_string(),
+            from_address: ADDRESS.to_string(),
+            to_address: "0x0987654321098765432109876543210987654321".to_string(),
+            amount: "2.0".to_string(),
+            fee: "0.001".to_string(),
+            status: "pending".to_string(),
+            created_at,
+            confirmed_at: None,
+            integrity_hash: String::new(),
+            fee_actual: None,
+            block_number: None,
+        })
+        .await
+        .unwrap();
+    // 余额快照任务当时记录的价格
+    let snapshot_at = (created_at - chrono::Duration::minutes(10)).timestamp();
+    server.storage.record_price_snapshot("ETH", "usd", "1800.5", snapshot_at).await.unwrap();
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { app, server, _dir: dir }
+}
+
+impl Harness {
+    async fn balance(&self) -> Value {
+        let res = self
+            .app
+            .get(&format!("/api/wallets/{}/balance", WALLET))
+            .add_query_param("network", "eth")
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .await;
+        res.assert_status_ok();
+        res.json()
+    }
+
+    async fn history(&self) -> Value {
+        let res = self.app.get(&format!("/api/wallets/{}/history", WALLET)).add_header("Authorization", API_KEY).await;
+        res.assert_status_ok();
+        res.json()
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_balance_and_history_fiat_values() {
+    let upstream = MockServer::start_async().await;
+    mock_price(&upstream, "eur", "1843.27");
+    mock_price(&upstream, "usd", "2000.5");
+    let h = build(&upstream).await;
+
+    // 会话user偏好 eur
+    let balance = h.balance().await;
+    assert_eq!(balance["balance"], "1.5");
+    assert_eq!(balance["fiat_value"], "2764.905");
+    assert_eq!(balance["fiat_currency"], "eur");
+
+    // API key 调用没有user偏好：默认货币 usd
+    let history = h.history().await;
+    assert_eq!(history["fiat_currency"], "usd");
+    let tx = history["transactions"].as_array().unwrap().iter().find(|t| t["network"] == "eth").unwrap();
+    assert_eq!(tx["fiat_value"], "4001");
+    assert_eq!(tx["fiat_value_at_time"], "3601");
+    assert_eq!(h.server.config.pricing.default_currency, "usd");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_feed_failure_omits_fiat_fields() {
+    let upstream = MockServer::start_async().await;
+    let failing = upstream.mock(|when, then| {
+        when.method(Method::GET).path("/simple/price");
+        then.status(500).json_body(json!({ "error": "internal" }));
+    });
+    let h = build(&upstream).await;
+
+    let balance = h.balance().await;
+    assert_eq!(balance["balance"], "1.5");
+    assert!(balance.get("fiat_value").is_none(), "{}", balance);
+    assert!(balance.get("fiat_currency").is_none(), "{}", balance);
+
+    let history = h.history().await;
+    let tx = history["transactions"].as_array().unwrap().iter().find(|t| t["network"] == "eth").unwrap();
+    assert_eq!(tx["amount"], "2.0");
+    assert!(tx.get("fiat_value").is_none(), "{}", tx);
+    // 历史价格来自本地快照，不依赖价格源
+    assert_eq!(tx["fiat_value_at_time"], "3601");
+    assert!(failing.hits() >= 1);
+}
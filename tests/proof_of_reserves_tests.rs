@@ -0,0 +1,245 @@
+//! 储备证明报告：生成、存档检索、签名validate与篡改检测
+
+mod util;
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use ethers::types::{Address, U256};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use defi_hot_wallet::blockchain::gas_oracle::GasOracle;
+use defi_hot_wallet::core::config::{ReserveReportConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::ops::proof_of_reserves::verify_report;
+
+const API_KEY: &str = "reserves-admin-key";
+const SESSION: &str = "reserves-session";
+const REPORTER: &str = "reporter";
+const PASSWORD: &str = "Rep0rt!Vault#2024";
+const COLD: &str = "0x00000000000000000000000000000000000000c0";
+const HOT: &str = "0x00000000000000000000000000000000000000b0";
+const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+const HEIGHT: u64 = 19_500_000;
+
+/// eth 正常；polygon 的 RPC 故障，拿不到区块高度
+struct ReserveChain {
+    native: HashMap<Address, u64>,
+    usdc: HashMap<Address, u64>,
+}
+
+#[async_trait]
+impl GasOracle for ReserveChain {
+    async fn gas_price(&self, _network: &str) -> Result<U256, WalletError> {
+        Ok(U256::zero())
+    }
+
+    async fn native_balance(&self, _network: &str, _address: &str) -> Result<U256, WalletError> {
+        Ok(U256::zero())
+    }
+
+    async fn block_number(&self, network: &str) -> Result<u64, WalletError> {
+        match network {
+            "eth" => Ok(HEIGHT),
+            _ => Err(WalletError::NetworkError("connection refused".to_string())),
+        }
+    }
+
+    async fn native_balance_at(&self, _network: &str, address: Address, block: u64) -> Result<U256, WalletError> {
+        assert_eq!(block, HEIGHT, "balances must be read at the recorded height");
+        Ok(U256::from(self.native.get(&address).copied().unwrap_or_default()))
+    }
+
+    async fn token_balance_at(
+        &self,
+        _network: &str,
+        token: Address,
+        holder: Address,
+        block: u64,
+    ) -> Result<U256, WalletError> {
+        assert_eq!(block, HEIGHT);
+        assert_eq!(token, USDC.parse::<Address>().unwrap());
+        Ok(U256::from(self.usdc.get(&holder).copied().unwrap_or_default()))
+    }
+
+    fn supports(&self, _network: &str) -> bool {
+        true
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    _dir: tempfile::TempDir,
+}
+
+async fn build(signing_wallet: Option<&str>) -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let config = WalletConfig {
+        reserve_reports: ReserveReportConfig {
+            signing_wallet: signing_wallet.map(str::to_string),
+            networks: vec!["eth".to_string(), "polygon".to_string()],
+            tokens: HashMap::from([("eth".to_string(), BTreeMap::from([("USDC".to_string(), USDC.to_string())]))]),
+            concurrency: 2,
+        },
+        ..util::memory_config()
+    };
+    let (cold, hot) = (COLD.parse::<Address>().unwrap(), HOT.parse::<Address>().unwrap());
+    let chain = ReserveChain {
+        native: HashMap::from([(cold, 5_000_000_000_000_000_000), (hot, 250_000_000_000_000_000)]),
+        usdc: HashMap::from([(hot, 1_500_000_000)]),
+    };
+    let server = util::test_server(dir.path(), config, Some(API_KEY)).await.with_gas_oracle(Arc::new(chain));
+
+    let user_id = util::sign_in(&server, "finance@example.com", SESSION).await;
+    server.user_db.link_wallet(&user_id, "cold", COLD, None).await.unwrap();
+    server.user_db.link_wallet(&user_id, "hot", HOT, None).await.unwrap();
+    server.wallet_manager.create_wallet(REPORTER, PASSWORD, false).await.unwrap();
+    server.storage.create_wallet_group("trading", &user_id, "admin").await.unwrap();
+    server.storage.add_wallet_group_member("trading", "hot", "admin").await.unwrap();
+
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, _dir: dir }
+}
+
+impl Harness {
+    async fn generate(&self, body: Value) -> axum_test::TestResponse {
+        self.app.post("/api/admin/reports/proof_of_reserves").add_header("X-API-KEY", API_KEY).json(&body).await
+    }
+
+    async fn verify(&self, document: &Value) -> Value {
+        let res = self
+            .app
+            .post("/api/reports/verify")
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .json(document)
+            .await;
+        res.assert_status_ok();
+        res.json()
+    }
+}
+
+fn total<'a>(report: &'a Value, network: &str, asset: &str) -> &'a Value {
+    report["totals"].as_array().unwrap().iter().find(|t| t["network"] == network && t["asset"] == asset).unwrap()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_report_over_managed_wallets_with_failing_network() {
+    let h = build(Some(REPORTER)).await;
+    let res = h.generate(json!({ "password": PASSWORD })).await;
+    res.assert_status_ok();
+    let doc: Value = res.json();
+    assert_eq!(doc["scheme"], "eip191");
+    let report = &doc["report"];
+
+    // polygon 拿不到高度：只记录 eth 的高度，polygon 行全部 unverified 但仍在报告中
+    assert_eq!(report["block_heights"], json!({ "eth": HEIGHT }));
+    let rows = report["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 6);
+    let unverified: Vec<&Value> = rows.iter().filter(|r| r["status"] == "unverified").collect();
+    assert_eq!(unverified.len(), 2);
+    assert!(unverified.iter().all(|r| r["network"] == "polygon" && r["amount"].is_null()));
+    assert!(unverified[0]["error"].as_str().unwrap().contains("Block height unavailable"));
+
+    let eth = total(report, "eth", "ETH");
+    assert_eq!(eth["amount"], "5250000000000000000");
+    assert_eq!(eth["verified_rows"], 2);
+    let usdc = total(report, "eth", "USDC");
+    assert_eq!(usdc["amount"], "1500000000");
+    assert_eq!(usdc["token"], USDC);
+    let matic = total(report, "polygon", "MATIC");
+    assert_eq!((matic["amount"].as_str(), matic["unverified_rows"].as_u64()), (Some("0"), Some(2)));
+
+    // 存档检索与生成时的文档一致；列表不含正文
+    let id = doc["id"].as_str().unwrap();
+    let res = h
+        .app
+        .get(&format!("/api/admin/reports/proof_of_reserves/{}", id))
+        .add_header("X-API-KEY", API_KEY)
+        .await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>(), doc);
+    let list: Value =
+        h.app.get("/api/admin/reports/proof_of_reserves").add_header("X-API-KEY", API_KEY).await.json();
+    assert_eq!(list["total"], 1);
+    assert_eq!(list["reports"][0]["id"], id);
+    assert_eq!(list["reports"][0]["unverified_rows"], 2);
+    assert!(list["reports"][0].get("document").is_none());
+
+    let res = h.app.get("/api/admin/reports/proof_of_reserves/missing").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_not_found();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_signature_round_trip_and_tamper_detection() {
+    let h = build(Some(REPORTER)).await;
+    let doc: Value = h.generate(json!({ "password": PASSWORD })).await.json();
+
+    let result = h.verify(&doc).await;
+    assert_eq!(result["valid"], true, "{}", result);
+    assert_eq!(result["recovered_signer"], doc["signer"]);
+    assert_eq!(result["rows"], 6);
+    // 离线 helper 得出相同结论
+    let offline =
+        verify_report(&doc["report"], doc["signer"].as_str().unwrap(), doc["signature"].as_str().unwrap());
+    assert!(offline.valid);
+
+    // 改一行金额：签名不再对应签名者，合计也对不上
+    let mut edited_row = doc.clone();
+    let row = edited_row["report"]["rows"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|r| r["wallet_name"] == "cold" && r["asset"] == "ETH")
+        .unwrap();
+    row["amount"] = json!("50000000000000000000");
+    let result = h.verify(&edited_row).await;
+    assert_eq!(result["valid"], false);
+    assert_eq!(result["signer_matches"], false);
+    assert_eq!(result["totals_match"], false);
+    assert_eq!(result["mismatched_totals"], json!(["eth:ETH"]));
+
+    // 连合计一起改：合计自洽，但签名仍然不对
+    let mut edited_totals = edited_row.clone();
+    let totals = edited_totals["report"]["totals"].as_array_mut().unwrap();
+    let eth = totals.iter_mut().find(|t| t["network"] == "eth" && t["asset"] == "ETH").unwrap();
+    eth["amount"] = json!("50250000000000000000");
+    let result = h.verify(&edited_totals).await;
+    assert_eq!(result["totals_match"], true);
+    assert_eq!(result["valid"], false);
+
+    // 声称的签名者不是恢复出的address
+    let mut wrong_signer = doc.clone();
+    wrong_signer["signer"] = json!("0x00000000000000000000000000000000000000aa");
+    assert_eq!(h.verify(&wrong_signer).await["valid"], false);
+
+    let res = h.app.post("/api/reports/verify").json(&doc).await;
+    res.assert_status_unauthorized();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_group_filter_and_signing_key_errors() {
+    let h = build(Some(REPORTER)).await;
+    let doc: Value = h.generate(json!({ "password": PASSWORD, "group": "trading" })).await.json();
+    assert_eq!(doc["report"]["group"], "trading");
+    let rows = doc["report"]["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 3);
+    assert!(rows.iter().all(|r| r["wallet_name"] == "hot"));
+    assert_eq!(total(&doc["report"], "eth", "ETH")["amount"], "250000000000000000");
+
+    let res = h.generate(json!({ "password": PASSWORD, "group": "nope" })).await;
+    res.assert_status_not_found();
+    let res = h.generate(json!({ "password": "wrong-password" })).await;
+    res.assert_status(StatusCode::UNAUTHORIZED);
+    let res = h.app.post("/api/admin/reports/proof_of_reserves").json(&json!({ "password": PASSWORD })).await;
+    res.assert_status_unauthorized();
+
+    let h = build(None).await;
+    let res = h.generate(json!({ "password": PASSWORD })).await;
+    res.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.json::<Value>()["error"]["code"], "REPORTING_KEY_NOT_CONFIGURED");
+}
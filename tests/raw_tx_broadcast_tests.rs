@@ -0,0 +1,182 @@
+//! `POST /api/admin/transactions/broadcast`：外部sign的原始transaction在广播前的
+//! chain id、EIP-155 重放保护与sign意图 nonce 冲突check
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, Eip1559TransactionRequest, TransactionRequest, H256};
+use ethers::utils::keccak256;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "raw-tx-admin-key";
+const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+#[derive(Default)]
+struct MockChain {
+    received: Mutex<Vec<H256>>,
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        tx.set_nonce(0u64);
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(1_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        let hash = H256::from(keccak256(&raw));
+        self.received.lock().unwrap().push(hash);
+        Ok(hash)
+    }
+
+    async fn transaction_known(&self, _network: &str, tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(self.received.lock().unwrap().contains(&tx_hash))
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    server: WalletServer,
+    chain: Arc<MockChain>,
+    signer: LocalWallet,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let chain = Arc::new(MockChain::default());
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap()
+    .with_broadcast_chain(chain.clone());
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { app, server, chain, signer: KEY.parse().unwrap(), _dir: dir }
+}
+
+fn eip1559(signer: &LocalWallet, chain_id: u64, nonce: u64) -> String {
+    let tx: TypedTransaction = Eip1559TransactionRequest::new()
+        .to(Address::repeat_byte(0x42))
+        .value(5u64)
+        .gas(21_000u64)
+        .max_fee_per_gas(2_000_000_000u64)
+        .max_priority_fee_per_gas(1_000_000_000u64)
+        .nonce(nonce)
+        .chain_id(chain_id)
+        .into();
+    let signature = signer.clone().with_chain_id(chain_id).sign_transaction_sync(&tx).unwrap();
+    format!("0x{}", hex::encode(tx.rlp_signed(&signature)))
+}
+
+impl Harness {
+    async fn broadcast(&self, raw: &str, allow_unprotected: bool) -> (StatusCode, Value) {
+        let res = self
+            .app
+            .post("/api/admin/transactions/broadcast")
+            .add_header("Authorization", API_KEY)
+            .json(&json!({ "network": "eth", "raw_tx": raw, "allow_unprotected": allow_unprotected }))
+            .await;
+        (res.status_code(), res.json())
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_broadcast_decodes_and_sends() {
+    let h = build().await;
+    let raw = eip1559(&h.signer, 1, 7);
+    let (status, body) = h.broadcast(&raw, false).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let hash = format!("{:?}", H256::from(keccak256(hex::decode(&raw[2..]).unwrap())));
+    assert_eq!(body["tx_hash"], hash.as_str());
+    assert_eq!(body["transaction"]["tx_type"], "eip1559");
+    assert_eq!(body["transaction"]["chain_id"], 1);
+    assert_eq!(body["transaction"]["nonce"], "7");
+    assert_eq!(body["transaction"]["from"], format!("{:?}", h.signer.address()).as_str());
+    assert_eq!(h.chain.received.lock().unwrap().len(), 1);
+
+    let res = h.app.post("/api/admin/transactions/broadcast").json(&json!({ "network": "eth", "raw_tx": raw })).await;
+    res.assert_status_unauthorized();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_broadcast_rejects_other_chain_and_unprotected() {
+    let h = build().await;
+
+    let (status, body) = h.broadcast(&eip1559(&h.signer, 11_155_111, 0), false).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "CHAIN_ID_MISMATCH");
+
+    let tx: TypedTransaction = TransactionRequest::new()
+        .to(Address::repeat_byte(0x42))
+        .value(5u64)
+        .gas(21_000u64)
+        .gas_price(1_000_000_000u64)
+        .nonce(0u64)
+        .into();
+    let signature = h.signer.sign_hash(tx.sighash()).unwrap();
+    let unprotected = format!("0x{}", hex::encode(tx.rlp_signed(&signature)));
+    let (status, body) = h.broadcast(&unprotected, false).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "UNPROTECTED_TRANSACTION");
+    assert!(h.chain.received.lock().unwrap().is_empty());
+
+    // 显式允许
+    let (status, body) = h.broadcast(&unprotected, true).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["transaction"]["chain_id"], Value::Null);
+
+    let (status, body) = h.broadcast("0x02f86b0180", false).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "INVALID_RAW_TRANSACTION");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_broadcast_rejects_managed_sender_nonce_conflict() {
+    let h = build().await;
+    h.server.storage.store_wallet("hot", b"blob", false).await.unwrap();
+    // 服务端为该address sign过 nonce 0
+    let to: Address = "0x000000000000000000000000000000000000dEaD".parse().unwrap();
+    let tx: TypedTransaction = TransactionRequest::new().to(to).value(1u64).into();
+    let ours = h.server.signing_intents.send("hot", "eth", &h.signer, tx).await.unwrap();
+
+    let (status, body) = h.broadcast(&eip1559(&h.signer, 1, 0), false).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["error"]["code"], "NONCE_IN_USE");
+    assert_eq!(h.chain.received.lock().unwrap().len(), 1);
+
+    // 下一个 nonce 不冲突
+    let (status, _) = h.broadcast(&eip1559(&h.signer, 1, 1), false).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(h.chain.received.lock().unwrap().iter().any(|hash| format!("{:?}", hash) == ours));
+}
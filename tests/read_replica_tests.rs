@@ -0,0 +1,184 @@
+//! 读写分离：只读查询走 read replica，写入与一致性读走 writer
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use tempfile::TempDir;
+
+use defi_hot_wallet::monitoring::WalletMetrics;
+use defi_hot_wallet::storage::{
+    journal_events, NewJournalEvent, ReadConsistency, TransactionRecord, WalletStorage,
+};
+
+fn db_url(dir: &TempDir, file: &str) -> String {
+    format!("sqlite://{}?mode=rwc", dir.path().join(file).display())
+}
+
+fn tx(id: &str, wallet_id: &str) -> TransactionRecord {
+    TransactionRecord {
+        id: id.to_string(),
+        wallet_id: wallet_id.to_string(),
+        tx_hash: format!("0x{:0>64}", id.len()),
+        network: "eth".to_string(),
+        from_address: "0x1111111111111111111111111111111111111111".to_string(),
+        to_address: "0x2222222222222222222222222222222222222222".to_string(),
+        amount: "1".to_string(),
+        fee: "0.001".to_string(),
+        status: "pending".to_string(),
+        created_at: Utc::now(),
+        confirmed_at: None,
+        integrity_hash: String::new(),
+        fee_actual: None,
+        block_number: None,
+    }
+}
+
+async fn wallet_id(storage: &WalletStorage, name: &str) -> String {
+    storage.list_wallets().await.unwrap().into_iter().find(|w| w.name == name).unwrap().id
+}
+
+struct Split {
+    /// writer 句柄，读请求路由到 replica
+    storage: WalletStorage,
+    /// 直接写 replica 文件，用来给两边灌入不同数据
+    replica: WalletStorage,
+    /// 不带 replica 的 writer 句柄，只看 writer 自己的数据
+    primary: WalletStorage,
+    metrics: Arc<WalletMetrics>,
+    _dir: TempDir,
+}
+
+async fn split() -> Split {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = TempDir::new().unwrap();
+    let replica = WalletStorage::new_with_url(&db_url(&dir, "replica.db")).await.unwrap();
+    let primary = WalletStorage::new_with_url(&db_url(&dir, "primary.db")).await.unwrap();
+    let metrics = Arc::new(WalletMetrics::new().unwrap());
+    let storage = WalletStorage::new_with_url(&db_url(&dir, "primary.db"))
+        .await
+        .unwrap()
+        .with_metrics(metrics.clone())
+        .with_read_replica(&db_url(&dir, "replica.db").replace("?mode=rwc", ""))
+        .await
+        .unwrap();
+    Split { storage, replica, primary, metrics, _dir: dir }
+}
+
+#[tokio::test]
+async fn test_reads_hit_replica_and_writes_hit_writer() {
+    let s = split().await;
+    assert!(s.storage.has_read_replica());
+
+    s.replica.store_wallet("replica_wallet", b"blob", false).await.unwrap();
+    let replica_wallet = wallet_id(&s.replica, "replica_wallet").await;
+    s.replica.store_transaction(&tx("replica-tx", &replica_wallet)).await.unwrap();
+    s.replica.log_action(&replica_wallet, "replica_action", "{}", None, None).await.unwrap();
+
+    s.storage.store_wallet("primary_wallet", b"blob", false).await.unwrap();
+    s.storage.log_action("primary", "primary_action", "{}", None, None).await.unwrap();
+
+    // 列表 / 历史 / 审计读 replica
+    let names: Vec<String> = s.storage.list_wallets().await.unwrap().into_iter().map(|w| w.name).collect();
+    assert_eq!(names, vec!["replica_wallet".to_string()]);
+    let history = s.storage.get_wallet_transactions(&replica_wallet).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].id, "replica-tx");
+    let audit = s.storage.get_audit_logs(None).await.unwrap();
+    // 创建wallet本身也会写一条 wallet_created 审计
+    let mut actions: Vec<&str> = audit.iter().map(|log| log.action.as_str()).collect();
+    actions.sort();
+    assert_eq!(actions, vec!["replica_action", "wallet_created"]);
+
+    // 写入只落在 writer
+    let names: Vec<String> = s.primary.list_wallets().await.unwrap().into_iter().map(|w| w.name).collect();
+    assert_eq!(names, vec!["primary_wallet".to_string()]);
+    assert!(s.replica.list_wallets().await.unwrap().iter().all(|w| w.name != "primary_wallet"));
+    // load_wallet 是读-改-写路径的一部分，留在 writer
+    assert!(s.storage.load_wallet("primary_wallet").await.is_ok());
+    assert!(s.storage.load_wallet("replica_wallet").await.is_err());
+
+    let exported = s.metrics.export_metrics().unwrap();
+    assert!(exported.contains("database_pool_operations_total{pool=\"reader\"}"));
+    assert!(exported.contains("database_pool_operations_total{pool=\"writer\"}"));
+}
+
+#[tokio::test]
+async fn test_read_after_write_uses_writer_until_replica_catches_up() {
+    let s = split().await;
+    // replica：journal 到 seq 2
+    s.replica.store_wallet("w", b"blob", false).await.unwrap();
+    let replica_wallet = wallet_id(&s.replica, "w").await;
+    s.replica.store_transaction(&tx("replica-tx", &replica_wallet)).await.unwrap();
+
+    // writer：journal 到 seq 3
+    s.storage.store_wallet("w", b"blob", false).await.unwrap();
+    let primary_wallet = wallet_id(&s.primary, "w").await;
+    s.storage.store_transaction(&tx("primary-tx-1", &primary_wallet)).await.unwrap();
+    s.storage.store_transaction(&tx("primary-tx-2", &primary_wallet)).await.unwrap();
+    let watermark = s.storage.write_watermark();
+    assert_eq!(watermark, 3);
+
+    // 普通读走落后的 replica，看不到刚写入的记录
+    assert!(s.storage.get_wallet_transactions(&primary_wallet).await.unwrap().is_empty());
+    // 同一请求内回读：replica 落后于 watermark，改读 writer
+    let read_back = s
+        .storage
+        .get_wallet_transactions_with(&primary_wallet, ReadConsistency::AfterSeq(watermark))
+        .await
+        .unwrap();
+    assert_eq!(read_back.len(), 2);
+    let primary_read = s
+        .storage
+        .get_wallet_transactions_with(&primary_wallet, ReadConsistency::Primary)
+        .await
+        .unwrap();
+    assert_eq!(primary_read.len(), 2);
+    // journal 长轮询同样不能读到落后的 replica
+    let events = s.storage.journal_events(0, 10).await.unwrap();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[2].entity_id, "primary-tx-2");
+
+    // replica 追上 watermark 后，一致性读回到 replica
+    s.replica
+        .record_event(&NewJournalEvent {
+            event_type: journal_events::LIMITS_CHANGED,
+            entity_type: "wallet",
+            entity_id: "w",
+            payload: serde_json::json!({}),
+        })
+        .await
+        .unwrap();
+    let from_replica = s
+        .storage
+        .get_wallet_transactions_with(&replica_wallet, ReadConsistency::AfterSeq(watermark))
+        .await
+        .unwrap();
+    assert_eq!(from_replica.len(), 1);
+    assert_eq!(from_replica[0].id, "replica-tx");
+    assert_eq!(s.storage.journal_events(2, 10).await.unwrap()[0].event_type, journal_events::LIMITS_CHANGED);
+}
+
+#[tokio::test]
+async fn test_without_replica_reads_see_own_writes() {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = TempDir::new().unwrap();
+    let storage = WalletStorage::new_with_url(&db_url(&dir, "single.db")).await.unwrap();
+    assert!(!storage.has_read_replica());
+
+    storage.store_wallet("solo", b"blob", false).await.unwrap();
+    let id = wallet_id(&storage, "solo").await;
+    storage.store_transaction(&tx("solo-tx", &id)).await.unwrap();
+    assert_eq!(storage.get_wallet_transactions(&id).await.unwrap().len(), 1);
+    assert_eq!(
+        storage
+            .get_wallet_transactions_with(&id, ReadConsistency::AfterSeq(storage.write_watermark()))
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // replica URL 必须是 sqlite
+    let err = storage.with_read_replica("postgres://replica/wallet").await.unwrap_err();
+    assert!(err.to_string().contains("sqlite"));
+}
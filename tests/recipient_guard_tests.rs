@@ -0,0 +1,165 @@
+//! 收款方合约检查（`acknowledge_contract_recipient`）集成测试
+
+mod util;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use ethers::abi::{self, Token};
+use ethers::providers::{MockProvider, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, Eip1559TransactionRequest, U256};
+use serde_json::{json, Value};
+
+use defi_hot_wallet::blockchain::gas_oracle::ProviderGasOracle;
+use defi_hot_wallet::core::abi::selector_from_signature;
+use defi_hot_wallet::core::config::{RecipientGuardConfig, WalletConfig};
+
+const API_KEY: &str = "recipient-guard-admin-key";
+const SESSION: &str = "recipient-guard-session";
+const ADDRESS: &str = "0x742d35cc6634c0532925a3b844bc454e4438f44e";
+const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+const ROUTER: &str = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D";
+const FRIEND: &str = "0x000000000000000000000000000000000000dEaD";
+const VAULT: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+struct Harness {
+    app: TestServer,
+    /// 与 gas oracle 共用的响应队列（LIFO）
+    rpc: MockProvider,
+    _dir: tempfile::TempDir,
+}
+
+async fn build(recipient_guard: RecipientGuardConfig) -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let config = WalletConfig { recipient_guard, ..util::memory_config() };
+    let (provider, rpc) = Provider::mocked();
+    let server = util::test_server(dir.path(), config, Some(API_KEY))
+        .await
+        .with_gas_oracle(Arc::new(ProviderGasOracle::<MockProvider>::new().with_provider("eth", provider)));
+
+    let user_id = util::sign_in(&server, "treasury@example.com", SESSION).await;
+    server.user_db.link_wallet(&user_id, "treasury", ADDRESS, None).await.unwrap();
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { app, rpc, _dir: dir }
+}
+
+/// 外部sign的 mainnet EIP-1559 transaction
+fn signed_tx(to: &str, value: u64, data: Vec<u8>) -> String {
+    let wallet = LocalWallet::from_bytes(&[0x07; 32]).unwrap().with_chain_id(1u64);
+    let tx: TypedTransaction = Eip1559TransactionRequest::new()
+        .to(to.parse::<Address>().unwrap())
+        .value(value)
+        .data(data)
+        .gas(60_000u64)
+        .max_fee_per_gas(20_000_000_000u64)
+        .max_priority_fee_per_gas(1_000_000_000u64)
+        .nonce(0u64)
+        .chain_id(1u64)
+        .into();
+    let signature = wallet.sign_transaction_sync(&tx).unwrap();
+    format!("0x{}", hex::encode(tx.rlp_signed(&signature)))
+}
+
+fn call(signature: &str, recipient: &str) -> Vec<u8> {
+    let mut data = selector_from_signature(signature).to_vec();
+    data.extend(abi::encode(&[Token::Address(recipient.parse().unwrap()), Token::Uint(U256::from(5_000_000u64))]));
+    data
+}
+
+impl Harness {
+    fn push_code(&self, code: &[u8]) {
+        self.rpc.push::<Bytes, _>(Bytes::from(code.to_vec())).unwrap();
+    }
+
+    async fn send(&self, to: &str, signed_tx: String, acknowledged: bool) -> axum_test::TestResponse {
+        self.app
+            .post("/api/wallets/treasury/send")
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .json(&json!({
+                "to": to,
+                "amount": "0.000000000000000001",
+                "network": "eth",
+                "signed_tx": signed_tx,
+                "acknowledge_contract_recipient": acknowledged,
+            }))
+            .await
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_plain_transfer_to_account_is_sent() {
+    let h = build(RecipientGuardConfig::default()).await;
+
+    // LIFO：先返回空代码；若第二次发送重新查询，会拿到合约代码而被拒绝
+    h.push_code(&[0x60, 0x80]);
+    h.push_code(&[]);
+    let res = h.send(FRIEND, signed_tx(FRIEND, 1, vec![]), false).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["status"], "sent");
+
+    h.send(FRIEND, signed_tx(FRIEND, 1, vec![]), false).await.assert_status_ok();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_transfer_to_token_contract_needs_acknowledgement() {
+    let h = build(RecipientGuardConfig::default()).await;
+
+    h.push_code(&[0x60, 0x80, 0x60, 0x40]);
+    let res = h.send(USDC, signed_tx(USDC, 1, vec![]), false).await;
+    res.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "RECIPIENT_IS_CONTRACT");
+    assert_eq!(body["error"]["details"]["recipient"], USDC);
+    assert_eq!(body["error"]["details"]["classification"]["kind"], "token_contract");
+    assert_eq!(body["error"]["details"]["classification"]["symbol"], "USDC");
+
+    // 确认后放行
+    h.send(USDC, signed_tx(USDC, 1, vec![]), true).await.assert_status_ok();
+    // 没有再推送响应：仍被拒绝说明结果来自缓存
+    let res = h.send(USDC, signed_tx(USDC, 1, vec![]), false).await;
+    res.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_erc20_transfer_recipient_is_inspected() {
+    let h = build(RecipientGuardConfig::default()).await;
+
+    // USDC.transfer(VAULT, ..)：检查的是 VAULT，而不是 USDC 合约
+    h.push_code(&[0x60, 0x80]);
+    let res = h.send(USDC, signed_tx(USDC, 0, call("transfer(address,uint256)", VAULT)), false).await;
+    res.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = res.json();
+    assert_eq!(body["error"]["details"]["recipient"], VAULT);
+    assert_eq!(body["error"]["details"]["classification"]["kind"], "unknown_contract");
+
+    // 其他合约调用不检查收款方（也不发 RPC）
+    h.send(USDC, signed_tx(USDC, 0, call("approve(address,uint256)", VAULT)), false).await.assert_status_ok();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_allowlisted_contract_needs_no_lookup() {
+    let h = build(RecipientGuardConfig {
+        interaction_allowlist: HashMap::from([("eth".to_string(), vec![ROUTER.to_string()])]),
+        ..Default::default()
+    })
+    .await;
+
+    // 若发起了 getCode，这条合约代码会让其他地址被拒绝
+    h.push_code(&[0x60, 0x80]);
+    h.send(ROUTER, signed_tx(ROUTER, 1, vec![]), false).await.assert_status_ok();
+    let res = h.send(VAULT, signed_tx(VAULT, 1, vec![]), false).await;
+    res.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    let h = build(RecipientGuardConfig { enabled: false, ..Default::default() }).await;
+    h.push_code(&[0x60, 0x80]);
+    h.send(VAULT, signed_tx(VAULT, 1, vec![]), false).await.assert_status_ok();
+}
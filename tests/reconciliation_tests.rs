@@ -0,0 +1,472 @@
+//! 链上对账：脚本化假链上每类差异都能被发现、`apply` 只修正安全的差异、
+//! 修正后再次对账只剩报告项，以及 `/api/admin/reconciliations` 的登记与分页
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use chrono::{DateTime, Utc};
+use ethers::types::{
+    Address, Block, Bytes, Filter, Log, Transaction, TransactionReceipt, ValueOrArray, H256, U256, U64,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::history::{transfer_topic, HistoryRpc, RpcHistory};
+use defi_hot_wallet::core::config::{BackfillConfig, StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::core::wallet_manager::reconcile::{
+    ReconcileError, ReconcileMode, ReconcileRange, ReconciliationReport,
+};
+use defi_hot_wallet::core::wallet_manager::WalletManager;
+use defi_hot_wallet::ops::backfill::HistoryBackfill;
+use defi_hot_wallet::ops::reconciliation::ReconciliationJob;
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{DiscrepancyKind, NetworkInit, TransactionRecord, WalletStorage, RUN_COMPLETED};
+
+const NETWORK: &str = "eth";
+const WALLET_NAME: &str = "treasury";
+const API_KEY: &str = "reconciliation-test-key-0123456789ab";
+const ETH: u128 = 1_000_000_000_000_000_000;
+const GAS_PRICE: u64 = 2_000_000_000;
+/// 21000 × 2 gwei
+const FEE: &str = "0.000042";
+const GENESIS: i64 = 1_700_000_000;
+
+fn wallet() -> Address {
+    Address::from_low_u64_be(0xa11ce)
+}
+
+fn funder() -> Address {
+    Address::from_low_u64_be(0xf00d)
+}
+
+fn other() -> Address {
+    Address::from_low_u64_be(0xb0b)
+}
+
+fn token() -> Address {
+    Address::from_low_u64_be(0x70ce)
+}
+
+/// 第 n 笔上链transaction的哈希（从 1 起）
+fn hash(n: u64) -> String {
+    format!("{:?}", H256::from_low_u64_be(n))
+}
+
+/// 区块 n 的出块时间
+fn mined_at(block: i64) -> i64 {
+    GENESIS + 12 * block
+}
+
+enum Op {
+    Send { from: Address, to: Address, value: u128, success: bool },
+    Token { from: Address, to: Address, amount: u128 },
+}
+
+/// 按脚本出块的链；与回填测试的假链不同，回执带 `from`/`to` 与 Transfer 日志
+#[derive(Default)]
+struct FakeChain {
+    blocks: Mutex<Vec<Block<Transaction>>>,
+    receipts: Mutex<HashMap<H256, TransactionReceipt>>,
+    logs: Mutex<Vec<Log>>,
+    minted: Mutex<u64>,
+}
+
+impl FakeChain {
+    fn new() -> Arc<Self> {
+        let chain = Self::default();
+        chain.mine(&[]);
+        Arc::new(chain)
+    }
+
+    fn mine(&self, ops: &[Op]) {
+        let mut blocks = self.blocks.lock().unwrap();
+        let number = blocks.len() as u64;
+        let mut logs = self.logs.lock().unwrap();
+        let mut transactions = Vec::new();
+        for op in ops {
+            let mut minted = self.minted.lock().unwrap();
+            *minted += 1;
+            let hash = H256::from_low_u64_be(*minted);
+            let (from, to, value, success) = match *op {
+                Op::Send { from, to, value, success } => (from, to, value, success),
+                Op::Token { from, .. } => (from, token(), 0, true),
+            };
+            let mut receipt_logs = Vec::new();
+            if let Op::Token { from, to, amount } = *op {
+                let mut data = [0u8; 32];
+                U256::from(amount).to_big_endian(&mut data);
+                let log = Log {
+                    address: token(),
+                    topics: vec![transfer_topic(), H256::from(from), H256::from(to)],
+                    data: Bytes::from(data.to_vec()),
+                    block_number: Some(U64::from(number)),
+                    transaction_hash: Some(hash),
+                    log_index: Some(U256::from(logs.len())),
+                    ..Default::default()
+                };
+                logs.push(log.clone());
+                receipt_logs.push(log);
+            }
+            transactions.push(Transaction {
+                hash,
+                from,
+                to: Some(to),
+                value: U256::from(value),
+                gas_price: Some(U256::from(GAS_PRICE)),
+                block_number: Some(U64::from(number)),
+                ..Default::default()
+            });
+            self.receipts.lock().unwrap().insert(
+                hash,
+                TransactionReceipt {
+                    transaction_hash: hash,
+                    block_number: Some(U64::from(number)),
+                    from,
+                    to: Some(to),
+                    logs: receipt_logs,
+                    gas_used: Some(U256::from(21_000u64)),
+                    effective_gas_price: Some(U256::from(GAS_PRICE)),
+                    status: Some(U64::from(success as u64)),
+                    ..Default::default()
+                },
+            );
+        }
+        blocks.push(Block {
+            number: Some(U64::from(number)),
+            timestamp: U256::from(mined_at(number as i64)),
+            transactions,
+            ..Default::default()
+        });
+    }
+}
+
+fn topic(filter: &Filter, index: usize) -> Option<H256> {
+    match &filter.topics[index] {
+        Some(ValueOrArray::Value(Some(topic))) => Some(*topic),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl HistoryRpc for FakeChain {
+    async fn block_number(&self, _network: &str) -> Result<u64, WalletError> {
+        Ok(self.blocks.lock().unwrap().len() as u64 - 1)
+    }
+
+    async fn block_with_transactions(
+        &self,
+        _network: &str,
+        number: u64,
+    ) -> Result<Option<Block<Transaction>>, WalletError> {
+        Ok(self.blocks.lock().unwrap().get(number as usize).cloned())
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        Ok(self.receipts.lock().unwrap().get(&tx_hash).cloned())
+    }
+
+    async fn logs(&self, _network: &str, filter: &Filter) -> Result<Vec<Log>, WalletError> {
+        let from = filter.get_from_block().unwrap().as_u64();
+        let to = filter.get_to_block().unwrap().as_u64();
+        let (sender, recipient) = (topic(filter, 1), topic(filter, 2));
+        Ok(self
+            .logs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|log| (from..=to).contains(&log.block_number.unwrap().as_u64()))
+            .filter(|log| sender.is_none_or(|t| log.topics[1] == t))
+            .filter(|log| recipient.is_none_or(|t| log.topics[2] == t))
+            .cloned()
+            .collect())
+    }
+}
+
+/// 区块 1..=8，第 n 笔transaction在区块 n：
+/// 1–3 转出、4 失败的转出、5 入账、6 代币入账、7 与wallet无关、8 转出
+fn chain() -> Arc<FakeChain> {
+    let chain = FakeChain::new();
+    for _ in 0..3 {
+        chain.mine(&[Op::Send { from: wallet(), to: other(), value: ETH, success: true }]);
+    }
+    chain.mine(&[Op::Send { from: wallet(), to: other(), value: 2 * ETH, success: false }]);
+    chain.mine(&[Op::Send { from: funder(), to: wallet(), value: 5 * ETH, success: true }]);
+    chain.mine(&[Op::Token { from: funder(), to: wallet(), amount: 1000 }]);
+    chain.mine(&[Op::Send { from: other(), to: funder(), value: ETH, success: true }]);
+    chain.mine(&[Op::Send { from: wallet(), to: other(), value: ETH / 2, success: true }]);
+    chain
+}
+
+/// 与链上分歧的记录：
+///
+/// | 哈希 | 记录 | 差异 |
+/// |------|------|------|
+/// | 1 | pending | 状态，可修正 |
+/// | 2 | 手续费为 0 | 手续费，可修正 |
+/// | 3 | 手续费 0.5 | 手续费，只报告 |
+/// | 4 | confirmed（链上失败） | 状态，只报告 |
+/// | 5、6 | 无记录 | 缺记录，可修正 |
+/// | 7 | wallet转出（链上与wallet无关） | 方向，只报告 |
+/// | 8 | 一致 | 无 |
+/// | 99 | confirmed（链上没有） | 孤儿，只报告 |
+/// | 100 | pending（未上链） | 计入 unmined |
+async fn seed(storage: &WalletStorage) {
+    let init = NetworkInit {
+        network: NETWORK.to_string(),
+        address: format!("{:?}", wallet()),
+        nonce: Some(0),
+        scan_from_block: Some(0),
+        error: None,
+    };
+    storage.store_wallet(WALLET_NAME, b"blob", false).await.unwrap();
+    storage.initialize_wallet_networks(WALLET_NAME, &[init]).await.unwrap();
+    let wallet_id = storage.wallet_id(WALLET_NAME).await.unwrap().unwrap();
+
+    let records = [(1, "pending", FEE), (2, "confirmed", "0"), (3, "confirmed", "0.5"), (4, "confirmed", FEE)];
+    let records = records.into_iter().chain([(7, "confirmed", FEE), (8, "confirmed", FEE)]);
+    let records = records.chain([(99, "confirmed", FEE), (100, "pending", FEE)]);
+    for (n, status, fee) in records {
+        let created_at = DateTime::from_timestamp(mined_at(n.min(8)) - 5, 0).unwrap();
+        storage
+            .store_transaction(&TransactionRecord {
+                id: format!("tx-{}", n),
+                wallet_id: wallet_id.clone(),
+                tx_hash: hash(n as u64),
+                network: NETWORK.to_string(),
+                from_address: format!("{:?}", wallet()),
+                to_address: format!("{:?}", other()),
+                amount: "1.0".to_string(),
+                fee: fee.to_string(),
+                status: status.to_string(),
+                created_at,
+                confirmed_at: (status == "confirmed").then(Utc::now),
+                integrity_hash: String::new(),
+                fee_actual: None,
+                block_number: None,
+            })
+            .await
+            .unwrap();
+    }
+}
+
+/// 创世块到区块 8 之后
+fn range() -> ReconcileRange {
+    ReconcileRange { from: GENESIS, to: mined_at(9) }
+}
+
+fn config() -> WalletConfig {
+    WalletConfig { backfill: BackfillConfig { blocks_per_batch: 3, ..Default::default() }, ..Default::default() }
+}
+
+async fn setup(chain: Arc<FakeChain>) -> (Arc<WalletStorage>, ReconciliationJob) {
+    // 修正写审计记录，其 MAC 需要 WALLET_ENC_KEY
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let storage = Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap());
+    seed(&storage).await;
+    let config = config();
+    let manager = Arc::new(WalletManager::new(&config).await.unwrap());
+    let source = Arc::new(RpcHistory::new(chain.clone()));
+    let backfill = Arc::new(HistoryBackfill::new(config.backfill.clone(), storage.clone(), source));
+    (storage.clone(), ReconciliationJob::new(manager, storage, backfill, chain))
+}
+
+async fn reconcile(job: &ReconciliationJob, mode: ReconcileMode) -> ReconciliationReport {
+    let networks = [NETWORK.to_string()];
+    job.run_direct(range(), &networks, mode, "ops@example.com", &CancellationToken::new()).await.unwrap()
+}
+
+/// (类型, 哈希, 可修正, 已修正)，按哈希排序
+fn findings(report: &ReconciliationReport) -> Vec<(DiscrepancyKind, String, bool, bool)> {
+    let mut findings: Vec<_> =
+        report.discrepancies.iter().map(|d| (d.kind, d.tx_hash.clone(), d.correctable, d.applied)).collect();
+    findings.sort_by(|a, b| a.1.cmp(&b.1));
+    findings
+}
+
+fn expected(applied: bool) -> Vec<(DiscrepancyKind, String, bool, bool)> {
+    vec![
+        (DiscrepancyKind::StatusMismatch, hash(1), true, applied),
+        (DiscrepancyKind::FeeMismatch, hash(2), true, applied),
+        (DiscrepancyKind::FeeMismatch, hash(3), false, false),
+        (DiscrepancyKind::StatusMismatch, hash(4), false, false),
+        (DiscrepancyKind::MissingRecord, hash(5), true, applied),
+        (DiscrepancyKind::MissingRecord, hash(6), true, applied),
+        (DiscrepancyKind::UnknownDirection, hash(7), false, false),
+        (DiscrepancyKind::OrphanRecord, hash(99), false, false),
+    ]
+}
+
+#[tokio::test]
+async fn test_report_mode_finds_every_discrepancy_and_changes_nothing() {
+    let (storage, job) = setup(chain()).await;
+
+    let report = reconcile(&job, ReconcileMode::Report).await;
+    assert_eq!(report.networks, vec![NETWORK.to_string()]);
+    assert!(!report.apply);
+    assert_eq!(findings(&report), expected(false));
+    let totals = &report.totals;
+    assert_eq!((totals.checked, totals.scanned, totals.unmined), (8, 7, 1));
+    assert_eq!((totals.discrepancies, totals.applied), (8, 0));
+
+    let status = report.discrepancies.iter().find(|d| d.tx_hash == hash(1)).unwrap();
+    assert_eq!((status.recorded.as_deref(), status.onchain.as_deref()), (Some("pending"), Some("confirmed")));
+    let fee = report.discrepancies.iter().find(|d| d.tx_hash == hash(3)).unwrap();
+    assert_eq!(fee.onchain.as_deref(), Some("0.000042000000000000"));
+    let direction = report.discrepancies.iter().find(|d| d.kind == DiscrepancyKind::UnknownDirection).unwrap();
+    assert_eq!((direction.recorded.as_deref(), direction.onchain.as_deref()), (Some("out"), Some("unrelated")));
+
+    // 报告模式不写任何东西
+    let pending = storage.transaction_for_wallet(WALLET_NAME, &hash(1)).await.unwrap().unwrap();
+    assert_eq!(pending.status, "pending");
+    assert!(storage.transaction_for_wallet(WALLET_NAME, &hash(5)).await.unwrap().is_none());
+    assert!(storage.ledger_entries(WALLET_NAME, NETWORK).await.unwrap().is_empty());
+    let audits = storage.get_audit_logs(Some(WALLET_NAME)).await.unwrap();
+    assert!(audits.iter().all(|a| a.action != "reconciliation.corrected"));
+}
+
+#[tokio::test]
+async fn test_apply_corrects_only_safe_discrepancies() {
+    let (storage, job) = setup(chain()).await;
+
+    let report = reconcile(&job, ReconcileMode::Apply).await;
+    assert!(report.apply);
+    assert_eq!(findings(&report), expected(true));
+    assert_eq!((report.totals.discrepancies, report.totals.applied), (8, 4));
+
+    let confirmed = storage.transaction_for_wallet(WALLET_NAME, &hash(1)).await.unwrap().unwrap();
+    assert_eq!(confirmed.status, "confirmed");
+    assert!(confirmed.confirmed_at.is_some());
+    let filled = storage.transaction_for_wallet(WALLET_NAME, &hash(2)).await.unwrap().unwrap();
+    assert_eq!(filled.fee, "0.000042000000000000");
+
+    // 只报告的记录原样保留
+    let wrong_fee = storage.transaction_for_wallet(WALLET_NAME, &hash(3)).await.unwrap().unwrap();
+    assert_eq!(wrong_fee.fee, "0.5");
+    let terminal = storage.transaction_for_wallet(WALLET_NAME, &hash(4)).await.unwrap().unwrap();
+    assert_eq!(terminal.status, "confirmed");
+    assert!(storage.transaction_for_wallet(WALLET_NAME, &hash(99)).await.unwrap().is_some());
+
+    // 缺失的记录与回填一样补上，连同账目
+    let received = storage.transaction_for_wallet(WALLET_NAME, &hash(5)).await.unwrap().unwrap();
+    assert_eq!((received.from_address, received.amount.as_str()), (format!("{:?}", funder()), "5.000000000000000000"));
+    assert_eq!(received.status, "confirmed");
+    assert!(storage.transaction_for_wallet(WALLET_NAME, &hash(6)).await.unwrap().is_some());
+    assert_eq!(storage.ledger_entries(WALLET_NAME, NETWORK).await.unwrap().len(), 2);
+
+    let audits = storage.get_audit_logs(Some(WALLET_NAME)).await.unwrap();
+    let corrections: Vec<_> = audits.iter().filter(|a| a.action == "reconciliation.corrected").collect();
+    assert_eq!(corrections.len(), 4);
+    assert!(corrections.iter().all(|a| a.details.as_deref().unwrap().contains("ops@example.com")));
+
+    // 修正之后再对账：只剩只报告的差异，apply 也不再改动
+    let again = reconcile(&job, ReconcileMode::Apply).await;
+    let left: Vec<_> = expected(false).into_iter().filter(|(_, _, correctable, _)| !correctable).collect();
+    assert_eq!(findings(&again), left);
+    let totals = &again.totals;
+    assert_eq!((totals.checked, totals.scanned, totals.discrepancies, totals.applied), (10, 7, 4, 0));
+}
+
+#[tokio::test]
+async fn test_invalid_requests_and_cancellation() {
+    let (_storage, job) = setup(chain()).await;
+    let cancel = CancellationToken::new();
+    let eth = [NETWORK.to_string()];
+
+    let empty = ReconcileRange { from: mined_at(5), to: mined_at(5) };
+    let err = job.run_direct(empty, &eth, ReconcileMode::Report, "ops", &cancel).await.unwrap_err();
+    assert!(matches!(err, ReconcileError::InvalidRange(..)));
+    let unknown = ["solana".to_string()];
+    let err = job.run_direct(range(), &unknown, ReconcileMode::Report, "ops", &cancel).await.unwrap_err();
+    assert!(matches!(err, ReconcileError::UnknownNetwork(n) if n == "solana"));
+
+    cancel.cancel();
+    let err = job.run_direct(range(), &eth, ReconcileMode::Apply, "ops", &cancel).await.unwrap_err();
+    assert!(matches!(err, ReconcileError::Cancelled));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_reconciliation_endpoints() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..config()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config.clone(),
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    let chain = chain();
+    let source = Arc::new(RpcHistory::new(chain.clone()));
+    let backfill = HistoryBackfill::new(config.backfill.clone(), server.storage.clone(), source);
+    let server = server.with_backfill(backfill);
+    let job =
+        ReconciliationJob::new(server.wallet_manager.clone(), server.storage.clone(), server.backfill.clone(), chain);
+    let server = server.with_reconciliation(job);
+    seed(&server.storage).await;
+    let reconciliation = server.reconciliation.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let body = json!({ "from": range().from, "to": range().to, "networks": [NETWORK], "requested_by": "ops" });
+    app.post("/api/admin/reconciliations").json(&body).await.assert_status_unauthorized();
+    let res = app.post("/api/admin/reconciliations").add_header("X-API-KEY", API_KEY);
+    let res = res.json(&json!({ "from": 100, "to": 100 })).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_RECONCILIATION");
+    let res = app.post("/api/admin/reconciliations").add_header("X-API-KEY", API_KEY);
+    let res = res.json(&json!({ "from": 0, "to": 100, "networks": ["solana"] })).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "UNKNOWN_NETWORK");
+    let res = app.get("/api/admin/reconciliations/missing").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_not_found();
+    assert_eq!(res.json::<Value>()["error"]["code"], "RECONCILIATION_NOT_FOUND");
+
+    let res = app.post("/api/admin/reconciliations").add_header("X-API-KEY", API_KEY).json(&body).await;
+    res.assert_status(axum::http::StatusCode::ACCEPTED);
+    let queued: Value = res.json();
+    assert_eq!((queued["status"].as_str(), queued["apply"].as_bool()), (Some("queued"), Some(false)));
+    let id = queued["id"].as_str().unwrap().to_string();
+    let res = app.get(&format!("/api/admin/reconciliations/{}", id)).add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    assert!(res.json::<Value>().get("findings").is_none(), "no report before the run completes");
+
+    // 测试服务不注册后台任务，直接处理队列
+    assert_eq!(reconciliation.run_queued(&CancellationToken::new()).await.unwrap(), 1);
+
+    let mut seen = Vec::new();
+    let mut url = format!("/api/admin/reconciliations/{}?limit=3", id);
+    loop {
+        let res = app.get(&url).add_header("X-API-KEY", API_KEY).await;
+        res.assert_status_ok();
+        let body: Value = res.json();
+        assert_eq!(body["status"], RUN_COMPLETED);
+        assert_eq!((body["discrepancies"].as_u64(), body["findings"]["total"].as_u64()), (Some(8), Some(8)));
+        let items = body["findings"]["items"].as_array().unwrap();
+        assert!(items.len() <= 3);
+        seen.extend(items.iter().map(|d| d["position"].as_i64().unwrap()));
+        match body["findings"]["next_cursor"].as_str() {
+            Some(cursor) => url = format!("/api/admin/reconciliations/{}?limit=3&cursor={}", id, cursor),
+            None => break,
+        }
+    }
+    assert_eq!(seen, (0..8).collect::<Vec<_>>());
+}
@@ -0,0 +1,318 @@
+//! HMAC 请求签名（`X-Key-Id` / `X-Timestamp` / `X-Nonce` / `X-Signature`）集成测试
+
+mod util;
+
+use axum::http::StatusCode;
+use axum_test::{TestRequest, TestServer};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Eip1559TransactionRequest};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use defi_hot_wallet::api::middleware::request_signing::{canonical_request, sign, NonceCache};
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::WalletConfig;
+use defi_hot_wallet::storage::WalletStorage;
+
+const API_KEY: &str = "request-signing-admin-key";
+const SESSION: &str = "request-signing-session";
+const ADDRESS: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+
+struct Harness {
+    app: TestServer,
+    server: WalletServer,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    build_with(|_| util::memory_config()).await
+}
+
+async fn build_with(config: impl FnOnce(&std::path::Path) -> WalletConfig) -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server(dir.path(), config(dir.path()), Some(API_KEY)).await;
+
+    let user_id = util::sign_in(&server, "signer@example.com", SESSION).await;
+    for name in ["treasury", "payroll"] {
+        server.user_db.link_wallet(&user_id, name, ADDRESS, None).await.unwrap();
+    }
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { app, server, _dir: dir }
+}
+
+/// 签发 token，返回完整响应
+async fn issue(h: &Harness, body: Value) -> Value {
+    let res = h
+        .app
+        .post("/api/wallets/treasury/tokens")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&body)
+        .await;
+    res.assert_status_ok();
+    res.json()
+}
+
+/// 返回 `(key_id, secret)`
+async fn issue_hmac(h: &Harness, capabilities: Value) -> (String, String) {
+    let body = issue(h, json!({ "capabilities": capabilities, "auth_mode": "hmac" })).await;
+    (body["id"].as_str().unwrap().to_string(), body["signing_secret"].as_str().unwrap().to_string())
+}
+
+struct Signed<'a> {
+    key_id: &'a str,
+    secret: &'a str,
+    nonce: &'a str,
+    timestamp: i64,
+}
+
+impl<'a> Signed<'a> {
+    fn now(key_id: &'a str, secret: &'a str, nonce: &'a str) -> Self {
+        Self { key_id, secret, nonce, timestamp: chrono::Utc::now().timestamp() }
+    }
+
+    fn headers(&self, request: TestRequest, method: &str, uri: &str, body: &[u8]) -> TestRequest {
+        let (path, query) = match uri.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (uri, None),
+        };
+        let timestamp = self.timestamp.to_string();
+        let canonical = canonical_request(method, path, query, &timestamp, self.nonce, body);
+        request
+            .add_header("X-Key-Id", self.key_id.to_string())
+            .add_header("X-Timestamp", timestamp)
+            .add_header("X-Nonce", self.nonce.to_string())
+            .add_header("X-Signature", sign(self.secret, &canonical))
+    }
+
+    fn get(&self, h: &Harness, uri: &str) -> TestRequest {
+        self.headers(h.app.get(uri), "GET", uri, b"")
+    }
+
+    fn post(&self, h: &Harness, uri: &str, body: &Value) -> TestRequest {
+        let bytes = serde_json::to_vec(body).unwrap();
+        self.headers(h.app.post(uri), "POST", uri, &bytes)
+            .bytes(bytes.into())
+            .content_type("application/json")
+    }
+}
+
+/// 外部sign的 mainnet EIP-1559 transaction
+fn send_body(amount: &str) -> Value {
+    let wallet = LocalWallet::from_bytes(&[0x07; 32]).unwrap().with_chain_id(1u64);
+    let tx: TypedTransaction = Eip1559TransactionRequest::new()
+        .to(RECIPIENT.parse::<Address>().unwrap())
+        .value(1u64)
+        .gas(21_000u64)
+        .max_fee_per_gas(20_000_000_000u64)
+        .max_priority_fee_per_gas(1_000_000_000u64)
+        .nonce(0u64)
+        .chain_id(1u64)
+        .into();
+    let signature = wallet.sign_transaction_sync(&tx).unwrap();
+    let signed_tx = format!("0x{}", hex::encode(tx.rlp_signed(&signature)));
+    json!({ "to": RECIPIENT, "amount": amount, "network": "eth", "signed_tx": signed_tx })
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_signed_requests_get_token_scope() {
+    let h = build().await;
+    let (key_id, secret) = issue_hmac(&h, json!(["read_balance"])).await;
+    assert!(secret.starts_with("wsk_"));
+
+    let res = Signed::now(&key_id, &secret, "get-1").get(&h, "/api/wallets/treasury/balance?network=eth").await;
+    res.assert_status_ok();
+
+    // 范围与 capability check与 bearer token 相同
+    let res = Signed::now(&key_id, &secret, "get-2").get(&h, "/api/wallets/payroll/balance?network=eth").await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(res.json::<Value>()["error"]["code"], "WALLET_SCOPE_MISMATCH");
+    let res = Signed::now(&key_id, &secret, "post-1").post(&h, "/api/wallets/treasury/send", &send_body("0.1")).await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(res.json::<Value>()["error"]["code"], "TOKEN_CAPABILITY_DENIED");
+    let res = Signed::now(&key_id, &secret, "get-3").get(&h, "/api/wallets/treasury/tokens").await;
+    res.assert_status(StatusCode::FORBIDDEN);
+
+    // body 经过validate后原样交给 handler；金额上限同样生效
+    let body = issue(&h, json!({ "capabilities": ["send"], "amount_cap": "0.5", "auth_mode": "hmac" })).await;
+    let (send_id, send_secret) = (body["id"].as_str().unwrap(), body["signing_secret"].as_str().unwrap());
+    assert_eq!(body["auth_mode"], "hmac");
+    assert!(body.get("token").is_none());
+    let res = Signed::now(send_id, send_secret, "post-2")
+        .post(&h, "/api/wallets/treasury/send", &send_body("0.5"))
+        .await;
+    res.assert_status_ok();
+    let res = Signed::now(send_id, send_secret, "post-3")
+        .post(&h, "/api/wallets/treasury/send", &send_body("0.6"))
+        .await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(res.json::<Value>()["error"]["code"], "TOKEN_AMOUNT_CAP_EXCEEDED");
+
+    // 签名覆盖 body 与 query
+    let signed = Signed::now(send_id, send_secret, "post-4");
+    let bytes = serde_json::to_vec(&send_body("0.1")).unwrap();
+    let res = signed
+        .headers(h.app.post("/api/wallets/treasury/send"), "POST", "/api/wallets/treasury/send", &bytes)
+        .bytes(serde_json::to_vec(&send_body("0.2")).unwrap().into())
+        .content_type("application/json")
+        .await;
+    res.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_SIGNATURE");
+    let signed = Signed::now(&key_id, &secret, "get-4");
+    let res = signed
+        .headers(
+            h.app.get("/api/wallets/treasury/balance?network=polygon"),
+            "GET",
+            "/api/wallets/treasury/balance?network=eth",
+            b"",
+        )
+        .await;
+    res.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_SIGNATURE");
+
+    let logs = h.server.storage.get_audit_logs(Some("treasury")).await.unwrap();
+    assert!(logs.iter().any(|log| log.action == "wallet_token.use"
+        && log.details.as_deref().is_some_and(|d| d.contains(&key_id))));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_clock_skew_is_rejected() {
+    let h = build().await;
+    let (key_id, secret) = issue_hmac(&h, json!(["read_balance"])).await;
+    let uri = "/api/wallets/treasury/balance?network=eth";
+    let now = chrono::Utc::now().timestamp();
+
+    for (nonce, timestamp) in [("old", now - 301), ("future", now + 301)] {
+        let res = Signed { key_id: &key_id, secret: &secret, nonce, timestamp }.get(&h, uri).await;
+        res.assert_status(StatusCode::UNAUTHORIZED);
+        assert_eq!(res.json::<Value>()["error"]["code"], "SIGNATURE_EXPIRED");
+    }
+    // 窗口内的偏差可以接受
+    let res = Signed { key_id: &key_id, secret: &secret, nonce: "late", timestamp: now - 280 }.get(&h, uri).await;
+    res.assert_status_ok();
+
+    let res = h
+        .app
+        .get(uri)
+        .add_header("X-Key-Id", key_id.clone())
+        .add_header("X-Timestamp", "yesterday")
+        .add_header("X-Nonce", "n")
+        .add_header("X-Signature", "00")
+        .await;
+    res.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_SIGNATURE");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_replayed_nonce_is_rejected() {
+    let h = build().await;
+    let (key_id, secret) = issue_hmac(&h, json!(["read_balance"])).await;
+    let uri = "/api/wallets/treasury/balance?network=eth";
+
+    let signed = Signed::now(&key_id, &secret, "once");
+    signed.get(&h, uri).await.assert_status_ok();
+    let res = signed.get(&h, uri).await;
+    res.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(res.json::<Value>()["error"]["code"], "NONCE_REPLAYED");
+
+    // 签名错误的请求不占用 nonce
+    let res = Signed::now(&key_id, "wsk_wrong", "fresh").get(&h, uri).await;
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_SIGNATURE");
+    Signed::now(&key_id, &secret, "fresh").get(&h, uri).await.assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_nonce_replay_across_storage_handles() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("wallets.db").display());
+    let first = NonceCache::new(Arc::new(WalletStorage::new_with_url(&url).await.unwrap()));
+    let second = NonceCache::new(Arc::new(WalletStorage::new_with_url(&url).await.unwrap()));
+    let now = chrono::Utc::now().timestamp();
+
+    assert!(first.claim("key-a", "n-1", now).await.unwrap());
+    // 另一个实例没有本地记录，靠数据库拒绝
+    assert!(!second.claim("key-a", "n-1", now).await.unwrap());
+    assert!(!first.claim("key-a", "n-1", now).await.unwrap());
+    // nonce 按 key 区分
+    assert!(second.claim("key-b", "n-1", now).await.unwrap());
+
+    // 保留期过后可以重新占用（此时时间戳窗口早已拒绝该请求）
+    assert!(second.claim("key-a", "n-1", now + 601).await.unwrap());
+
+    // 本地缓存满时丢弃的条目仍由数据库拒绝
+    let small = NonceCache::new(Arc::new(WalletStorage::new_with_url(&url).await.unwrap())).with_capacity(1);
+    assert!(small.claim("key-c", "n-1", now).await.unwrap());
+    assert!(small.claim("key-c", "n-2", now).await.unwrap());
+    assert!(!small.claim("key-c", "n-1", now).await.unwrap());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_signing_secret_is_not_stored_in_plaintext() {
+    let h = build_with(util::file_config).await;
+    let (key_id, secret) = issue_hmac(&h, json!(["read_balance"])).await;
+
+    // 数据库文件（含 WAL）里找不到 secret，备份也就带不走它
+    for entry in std::fs::read_dir(h._dir.path()).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_file() {
+            let bytes = std::fs::read(&path).unwrap();
+            assert!(!bytes.windows(secret.len()).any(|w| w == secret.as_bytes()), "{}", path.display());
+        }
+    }
+    Signed::now(&key_id, &secret, "p-1").get(&h, "/api/wallets/treasury/balance?network=eth").await.assert_status_ok();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_bearer_and_hmac_keys_do_not_interoperate() {
+    let h = build().await;
+    let uri = "/api/wallets/treasury/balance?network=eth";
+    let bearer = issue(&h, json!({ "capabilities": ["read_balance"] })).await;
+    let (bearer_token, bearer_id) = (bearer["token"].as_str().unwrap(), bearer["id"].as_str().unwrap());
+    assert_eq!(bearer["auth_mode"], "bearer");
+    assert!(bearer.get("signing_secret").is_none());
+    let (key_id, secret) = issue_hmac(&h, json!(["read_balance"])).await;
+
+    // bearer token 不能当作签名 key
+    let res = Signed::now(bearer_id, bearer_token, "b-1").get(&h, uri).await;
+    res.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_TOKEN");
+
+    // 签名 secret 不能当作 bearer token
+    h.app
+        .get(uri)
+        .add_header("Authorization", format!("Bearer {}", secret))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    h.app
+        .get(uri)
+        .add_header("Authorization", format!("Bearer wtk_{}", secret.trim_start_matches("wsk_")))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    // 内部头不能由客户端伪造
+    h.app
+        .get(uri)
+        .add_header("X-Verified-Key-Id", key_id.clone())
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    // 两种模式各自有效
+    h.app.get(uri).add_header("Authorization", format!("Bearer {}", bearer_token)).await.assert_status_ok();
+    Signed::now(&key_id, &secret, "h-1").get(&h, uri).await.assert_status_ok();
+
+    let res = h
+        .app
+        .post("/api/wallets/treasury/tokens")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "capabilities": ["read_balance"], "auth_mode": "basic" }))
+        .await;
+    res.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_AUTH_MODE");
+}
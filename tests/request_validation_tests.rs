@@ -0,0 +1,217 @@
+//! 请求参数validate（`api::validators`）的性质测试与错误体快照
+//!
+//! 对抗输入（形近字符、NUL、超长、前导零、大小写混合hex）下：不 panic，
+//! 且接受/拒绝与测试内独立实现的参考文法完全一致。
+
+use axum_test::TestServer;
+use ethers::types::Address;
+use proptest::prelude::*;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::api::types::ApiError;
+use defi_hot_wallet::api::validators::{Amount, EvmAddress, NetworkName, ParamError, TxHash, WalletNameParam};
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+
+const ERROR_BODIES: &str = include_str!("fixtures/validation/error_bodies.json");
+
+// ---- 参考文法 ----
+
+fn ref_wallet_name(s: &str) -> bool {
+    (1..=64).contains(&s.len()) && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
+fn ref_address(s: &str) -> bool {
+    let Some(hex) = s.strip_prefix("0x") else { return false };
+    if hex.len() != 40 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return false;
+    }
+    let mixed = hex.bytes().any(|b| b.is_ascii_lowercase()) && hex.bytes().any(|b| b.is_ascii_uppercase());
+    !mixed || ethers::utils::to_checksum(&Address::from_str(s).unwrap(), None) == s
+}
+
+fn ref_amount(s: &str) -> bool {
+    let digits = |p: &str| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit());
+    let (int, frac) = match s.find('.') {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+    s.len() <= 80
+        && digits(int)
+        && (int == "0" || !int.starts_with('0'))
+        && frac.is_none_or(|f| digits(f) && f.len() <= 18)
+        && s.bytes().any(|b| (b'1'..=b'9').contains(&b))
+}
+
+fn ref_tx_hash(s: &str) -> bool {
+    let hex = s.strip_prefix("0x").unwrap_or(s);
+    hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// ---- 对抗输入 ----
+
+/// 与 ASCII 形近或不可见的字符
+const CONFUSABLES: &[char] =
+    &['\u{0430}', '\u{043E}', '\u{0455}', '\u{FF10}', '\u{0661}', '\u{200B}', '\u{00A0}', '\0', 'ℓ', 'Ｏ'];
+
+fn with_confusable(base: impl Strategy<Value = String>) -> impl Strategy<Value = String> {
+    (base, any::<prop::sample::Index>(), prop::sample::select(CONFUSABLES)).prop_map(|(s, idx, c)| {
+        let mut chars: Vec<char> = s.chars().collect();
+        chars.insert(idx.index(chars.len() + 1), c);
+        chars.into_iter().collect()
+    })
+}
+
+fn adversarial() -> impl Strategy<Value = String> {
+    prop_oneof![
+        any::<String>(),
+        "[a-zA-Z0-9_.\\-/]{0,80}",
+        "0x[0-9a-fA-F]{38,42}",
+        "0[0-9]{0,6}(\\.[0-9]{0,24})?",
+        "[0-9]{0,100}\\.?[0-9]{0,30}",
+        "(0x)?[0-9a-fA-F]{60,68}",
+        with_confusable("[a-z0-9_]{1,20}"),
+        with_confusable("0x[0-9a-f]{40}"),
+        with_confusable("[1-9][0-9]{0,3}\\.[0-9]{1,4}"),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn wallet_name_matches_grammar(s in adversarial()) {
+        prop_assert_eq!(WalletNameParam::try_from(s.as_str()).is_ok(), ref_wallet_name(&s), "{:?}", s);
+    }
+
+    #[test]
+    fn address_matches_grammar(s in adversarial()) {
+        prop_assert_eq!(EvmAddress::try_from(s.as_str()).is_ok(), ref_address(&s), "{:?}", s);
+    }
+
+    #[test]
+    fn amount_matches_grammar(s in adversarial()) {
+        prop_assert_eq!(Amount::try_from(s.as_str()).is_ok(), ref_amount(&s), "{:?}", s);
+    }
+
+    #[test]
+    fn tx_hash_matches_grammar(s in adversarial()) {
+        prop_assert_eq!(TxHash::try_from(s.as_str()).is_ok(), ref_tx_hash(&s), "{:?}", s);
+    }
+
+    #[test]
+    fn network_rejects_everything_but_known_names(s in adversarial()) {
+        let known = ["eth", "ethereum", "sepolia", "polygon", "polygon-testnet", "bsc", "binance", "bnb", "bsctestnet", "btc", "bitcoin"];
+        prop_assert_eq!(NetworkName::try_from(s.as_str()).is_ok(), known.contains(&s.as_str()));
+    }
+
+    /// 任意大小写组合的地址：全小写/全大写恒接受，混合大小写仅接受 EIP-55 形式
+    #[test]
+    fn mixed_case_address_requires_checksum(hex in "[0-9a-fA-F]{40}") {
+        let addr = format!("0x{}", hex);
+        let checksummed = ethers::utils::to_checksum(&Address::from_str(&addr).unwrap(), None);
+        prop_assert!(EvmAddress::try_from(addr.to_lowercase().as_str()).is_ok());
+        prop_assert!(EvmAddress::try_from(checksummed.as_str()).is_ok());
+        let accepted = EvmAddress::try_from(addr.as_str()).is_ok();
+        let mixed = hex.bytes().any(|b| b.is_ascii_lowercase()) && hex.bytes().any(|b| b.is_ascii_uppercase());
+        prop_assert_eq!(accepted, !mixed || addr == checksummed);
+    }
+
+    /// 被接受的金额原样保留
+    #[test]
+    fn accepted_amount_round_trips(int in "[1-9][0-9]{0,20}", frac in proptest::option::of("[0-9]{1,18}")) {
+        let s = match frac {
+            Some(f) => format!("{}.{}", int, f),
+            None => int,
+        };
+        let amount = Amount::try_from(s.as_str()).unwrap();
+        prop_assert_eq!(amount.as_str(), s.as_str());
+    }
+}
+
+// ---- 错误体快照 ----
+
+fn rejection(param: &str, input: &str) -> ParamError {
+    match param {
+        "wallet_name" => WalletNameParam::try_from(input).unwrap_err(),
+        "address" => EvmAddress::try_from(input).unwrap_err(),
+        "amount" => Amount::try_from(input).unwrap_err(),
+        "network" => NetworkName::try_from(input).unwrap_err(),
+        "evm_network" => NetworkName::try_from(input).unwrap().require_evm().unwrap_err(),
+        "tx_hash" => TxHash::try_from(input).unwrap_err(),
+        other => panic!("unknown param kind in fixture: {}", other),
+    }
+}
+
+#[test]
+fn test_error_bodies_match_snapshot() {
+    let cases: Vec<Value> = serde_json::from_str(ERROR_BODIES).unwrap();
+    assert!(!cases.is_empty());
+    for case in cases {
+        let input = case["input"].as_str().unwrap();
+        let error: ApiError = rejection(case["param"].as_str().unwrap(), input).into();
+        assert_eq!(u64::from(error.status().as_u16()), case["status"].as_u64().unwrap(), "{:?}", input);
+        let body = json!({ "code": error.code().as_str(), "message": error.message() });
+        assert_eq!(body, case["body"], "{:?}", input);
+    }
+}
+
+// ---- 提取器在 handler 逻辑前拒绝 ----
+
+#[tokio::test]
+async fn test_extractors_reject_before_handler_logic() {
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(1),
+            connection_timeout_seconds: Some(30),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let api_key = Some(zeroize::Zeroizing::new(b"validation_key".to_vec()));
+    let server = WalletServer::new_for_test("127.0.0.1".to_string(), 0, config, api_key, None).await.unwrap();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    // 签名数不足本会触发 "Insufficient signatures"；非法金额先被拒绝
+    let res = app
+        .post("/api/wallets/nowallet/send_multi_sig")
+        .add_header("Authorization", "validation_key")
+        .json(&json!({
+            "to": "0x742d35Cc6634C0532925a3b844Bc454e4438f44e",
+            "amount": "01.5",
+            "network": "eth",
+            "signatures": ["sig1"]
+        }))
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_AMOUNT");
+
+    let res = app
+        .post("/api/wallets/nowallet/send_multi_sig")
+        .add_header("Authorization", "validation_key")
+        .json(&json!({
+            "to": "0x742d35Cc6634C0532925a3b844Bc454e4438f44e",
+            "amount": "1",
+            "network": "btc",
+            "signatures": ["sig1", "sig2"]
+        }))
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "UNSUPPORTED_NETWORK");
+
+    let res = app.get("/api/transactions/not-a-hash/status").add_header("Authorization", "validation_key").await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_TX_HASH");
+
+    // 无法解析的 body 仍返回统一错误体
+    let res = app
+        .post("/api/bridge")
+        .add_header("Authorization", "validation_key")
+        .content_type("application/json")
+        .bytes("{not json".into())
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_REQUEST");
+}
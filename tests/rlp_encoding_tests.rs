@@ -0,0 +1,247 @@
+//! core::rlp：与 ethers 编码逐字节比对、随机往返、畸形输入与非规范形式
+
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip2930::{AccessList, AccessListItem};
+use ethers::types::{Address, Eip1559TransactionRequest, TransactionRequest, H256, U256};
+use proptest::prelude::*;
+
+use defi_hot_wallet::core::config::{SecurityConfig, TxEncoding};
+use defi_hot_wallet::core::rlp::tx::sign_transaction;
+use defi_hot_wallet::core::rlp::{
+    AccessListEntry, Eip1559Tx, Item, LegacyTx, RlpError, SignedTx, TxPayload, TxSignature,
+};
+
+const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+fn wallet(chain_id: u64) -> LocalWallet {
+    KEY.parse::<LocalWallet>().unwrap().with_chain_id(chain_id)
+}
+
+fn to() -> Address {
+    "0x3535353535353535353535353535353535353535".parse().unwrap()
+}
+
+fn access_list() -> AccessList {
+    AccessList(vec![
+        AccessListItem { address: to(), storage_keys: vec![H256::zero(), H256::repeat_byte(0xff)] },
+        AccessListItem { address: Address::repeat_byte(0x11), storage_keys: Vec::new() },
+    ])
+}
+
+/// 覆盖三种类型、合约创建、空/长 calldata、零值与极值
+fn corpus() -> Vec<TypedTransaction> {
+    let legacy = TransactionRequest::new()
+        .nonce(9)
+        .gas_price(20_000_000_000u64)
+        .gas(21_000)
+        .to(to())
+        .value(U256::exp10(18))
+        .chain_id(1);
+    let eip1559 = Eip1559TransactionRequest::new()
+        .nonce(0)
+        .max_priority_fee_per_gas(1_500_000_000u64)
+        .max_fee_per_gas(30_000_000_000u64)
+        .gas(90_000)
+        .to(to())
+        .value(0)
+        .chain_id(1);
+
+    vec![
+        legacy.clone().into(),
+        legacy.clone().chain_id(137).data(vec![0xa9, 0x05, 0x9c, 0xbb]).into(),
+        TransactionRequest::new().nonce(1).gas_price(1).gas(3_000_000).data(vec![0x60; 300]).chain_id(56).into(),
+        legacy.clone().value(U256::MAX).nonce(U256::from(u64::MAX)).into(),
+        legacy.clone().chain_id(5).with_access_list(access_list()).into(),
+        eip1559.clone().into(),
+        eip1559.clone().access_list(access_list()).data(vec![0x42; 55]).into(),
+        eip1559.clone().data(vec![0x42; 56]).chain_id(11155111).into(),
+        Eip1559TransactionRequest::new().nonce(7).gas(500_000).data(vec![0xfe; 1024]).chain_id(10).into(),
+    ]
+}
+
+#[test]
+fn test_matches_ethers_byte_for_byte() {
+    for tx in corpus() {
+        let chain_id = tx.chain_id().unwrap().as_u64();
+        let signer = wallet(chain_id);
+        let signature = signer.sign_transaction_sync(&tx).unwrap();
+        let expected = tx.rlp_signed(&signature);
+
+        let payload = TxPayload::try_from(&tx).unwrap();
+        assert_eq!(payload.sighash(), tx.sighash(), "{:?}", tx);
+        let signed = SignedTx { signature: TxSignature::for_payload(&payload, &signature).unwrap(), payload };
+        assert_eq!(signed.encode().unwrap(), expected.to_vec(), "{:?}", tx);
+        assert_eq!(signed.hash().unwrap(), tx.hash(&signature));
+        assert_eq!(SignedTx::decode(&expected).unwrap(), signed);
+
+        // 两种编码器的sign结果一致（RFC 6979 确定性签名）
+        let native = sign_transaction(TxEncoding::Native, &signer, &tx).unwrap();
+        assert_eq!(native, sign_transaction(TxEncoding::Ethers, &signer, &tx).unwrap());
+    }
+}
+
+#[test]
+fn test_pre_eip155_legacy_matches_ethers() {
+    let tx: TypedTransaction = TransactionRequest::new().nonce(3).gas_price(1).gas(21_000).to(to()).value(5).into();
+    let signature = wallet(1).sign_hash(tx.sighash()).unwrap();
+    let payload = TxPayload::try_from(&tx).unwrap();
+    assert_eq!(payload.chain_id(), None);
+    let signed = SignedTx { signature: TxSignature::for_payload(&payload, &signature).unwrap(), payload };
+    assert_eq!(signed.encode().unwrap(), tx.rlp_signed(&signature).to_vec());
+    assert!(matches!(signed.signature.v, 27 | 28));
+}
+
+#[test]
+fn test_rejects_malformed_transactions() {
+    let valid = {
+        let tx = corpus().remove(5);
+        let signature = wallet(1).sign_transaction_sync(&tx).unwrap();
+        tx.rlp_signed(&signature).to_vec()
+    };
+    assert!(SignedTx::decode(&valid).is_ok());
+
+    // 截断、追加、未知类型、字段数错误
+    for cut in 0..valid.len() {
+        assert!(SignedTx::decode(&valid[..cut]).is_err(), "prefix of {} bytes", cut);
+    }
+    let mut trailing = valid.clone();
+    trailing.push(0);
+    assert_eq!(SignedTx::decode(&trailing), Err(RlpError::TrailingBytes(1)));
+    let mut unknown = valid.clone();
+    unknown[0] = 0x03;
+    assert_eq!(SignedTx::decode(&unknown), Err(RlpError::UnknownTxType(0x03)));
+    let short = [&[0x02][..], &Item::List(vec![Item::u64(1); 11]).encode()].concat();
+    assert_eq!(SignedTx::decode(&short), Err(RlpError::FieldCount { expected: 12, actual: 11 }));
+
+    let mut fields = vec![Item::u64(1); 12];
+    fields[5] = Item::bytes(vec![0x35; 19]);
+    fields[8] = Item::List(Vec::new());
+    let bad_to = [&[0x02][..], &Item::List(fields.clone()).encode()].concat();
+    assert_eq!(SignedTx::decode(&bad_to), Err(RlpError::InvalidLength { expected: 20, actual: 19 }));
+
+    fields[5] = Item::bytes(Vec::new());
+    fields[9] = Item::u64(27);
+    let bad_parity = [&[0x02][..], &Item::List(fields.clone()).encode()].concat();
+    assert!(matches!(SignedTx::decode(&bad_parity), Err(RlpError::InvalidSignature(_))));
+
+    // 整数字段带前导零
+    fields[9] = Item::u64(1);
+    fields[1] = Item::bytes(vec![0, 1]);
+    let leading_zero = [&[0x02][..], &Item::List(fields).encode()].concat();
+    assert_eq!(SignedTx::decode(&leading_zero), Err(RlpError::LeadingZeroInteger));
+
+    for raw in ["", "00", "80", "c0", "f8", "b8", "bf0000000000000001", "ff0000000000000001", "02", "02c0", "01c180"] {
+        let raw = hex::decode(raw).unwrap();
+        assert!(SignedTx::decode(&raw).is_err(), "{}", hex::encode(&raw));
+    }
+}
+
+#[test]
+fn test_encode_checks_v_against_the_payload() {
+    let payload = TxPayload::Legacy(LegacyTx { chain_id: Some(1), ..Default::default() });
+    let signed = SignedTx { payload, signature: TxSignature { v: 27, r: U256::one(), s: U256::one() } };
+    assert!(matches!(signed.encode(), Err(RlpError::InvalidSignature(_))));
+}
+
+#[test]
+fn test_encoder_is_selected_by_the_security_section() {
+    // hot_wallet 启动时整个 [security] 段按 SecurityConfig 反序列化
+    let doc: toml::Value = toml::from_str("[security]\ntx_encoding = \"native\"\n").unwrap();
+    let security: SecurityConfig = doc["security"].clone().try_into().unwrap();
+    assert_eq!(security.tx_encoding, TxEncoding::Native);
+    assert_eq!(SecurityConfig::default().tx_encoding, TxEncoding::Ethers);
+}
+
+fn item() -> impl Strategy<Value = Item> {
+    let leaf = proptest::collection::vec(any::<u8>(), 0..80).prop_map(Item::Bytes);
+    leaf.prop_recursive(4, 64, 8, |inner| proptest::collection::vec(inner, 0..8).prop_map(Item::List))
+}
+
+fn u256() -> impl Strategy<Value = U256> {
+    prop_oneof![any::<u64>().prop_map(U256::from), any::<[u8; 32]>().prop_map(|b| U256::from_big_endian(&b))]
+}
+
+fn eip1559() -> impl Strategy<Value = TxPayload> {
+    (
+        (any::<u64>(), u256(), u256(), u256(), u256()),
+        (proptest::option::of(any::<[u8; 20]>()), u256(), proptest::collection::vec(any::<u8>(), 0..300)),
+        proptest::collection::vec((any::<[u8; 20]>(), proptest::collection::vec(any::<[u8; 32]>(), 0..3)), 0..3),
+    )
+        .prop_map(|((chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas, gas), (to, value, data), list)| {
+            TxPayload::Eip1559(Eip1559Tx {
+                chain_id,
+                nonce,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                gas,
+                to: to.map(Address::from),
+                value,
+                data,
+                access_list: list
+                    .into_iter()
+                    .map(|(address, keys)| AccessListEntry {
+                        address: Address::from(address),
+                        storage_keys: keys.into_iter().map(H256::from).collect(),
+                    })
+                    .collect(),
+            })
+        })
+}
+
+proptest! {
+    #[test]
+    fn item_round_trips(item in item()) {
+        prop_assert_eq!(Item::decode(&item.encode()), Ok(item));
+    }
+
+    /// 规范形式唯一：能解码的输入重新编码后必须逐字节相同
+    #[test]
+    fn random_bytes_never_panic_and_decode_canonically(raw in proptest::collection::vec(any::<u8>(), 0..200)) {
+        if let Ok(item) = Item::decode(&raw) {
+            prop_assert_eq!(item.encode(), raw.clone());
+        }
+        if let Ok(tx) = SignedTx::decode(&raw) {
+            prop_assert_eq!(tx.encode().unwrap(), raw);
+        }
+    }
+
+    #[test]
+    fn legacy_and_typed_transactions_round_trip(
+        payload in eip1559(),
+        legacy_chain in proptest::option::of(0u64..1_000_000),
+        parity in any::<bool>(),
+        r in u256(),
+        s in u256(),
+    ) {
+        let TxPayload::Eip1559(tx) = &payload else { unreachable!() };
+        let legacy = TxPayload::Legacy(LegacyTx {
+            chain_id: legacy_chain,
+            nonce: tx.nonce,
+            gas_price: tx.max_fee_per_gas,
+            gas: tx.gas,
+            to: tx.to,
+            value: tx.value,
+            data: tx.data.clone(),
+        });
+        for payload in [payload.clone(), legacy] {
+            let signed = SignedTx { signature: TxSignature { v: payload.v(parity).unwrap(), r, s }, payload };
+            let raw = signed.encode().unwrap();
+            prop_assert_eq!(SignedTx::decode(&raw), Ok(signed));
+        }
+    }
+
+    #[test]
+    fn canonical_violations_are_detected(byte in 0u8..0x80, payload in proptest::collection::vec(any::<u8>(), 2..56)) {
+        // 单字节被包在字符串头中
+        prop_assert_eq!(Item::decode(&[0x81, byte]), Err(RlpError::NonCanonicalSingleByte));
+        // 短载荷使用长格式长度
+        let long_form = [&[0xb8, payload.len() as u8][..], &payload].concat();
+        prop_assert_eq!(Item::decode(&long_form), Err(RlpError::NonCanonicalLength(payload.len())));
+        let zero_prefixed = [&[0xb9, 0x00, payload.len() as u8][..], &payload].concat();
+        prop_assert_eq!(Item::decode(&zero_prefixed), Err(RlpError::LeadingZeroLength));
+        // 整数前导零
+        let padded = Item::bytes([&[0u8][..], &payload[..payload.len().min(7)]].concat());
+        prop_assert_eq!(padded.as_u64(), Err(RlpError::LeadingZeroInteger));
+    }
+}
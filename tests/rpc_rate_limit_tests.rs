@@ -0,0 +1,198 @@
+//! 出站 RPC 限流：令牌桶跑在暂停的 tokio 时钟上，validate三种调用方的溢出策略、
+//! 后台任务把下一个令牌让给等待中的user调用，以及主节点配额用尽时切到备用节点
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::providers::{MockProvider, Provider};
+use ethers::types::U64;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+use defi_hot_wallet::blockchain::failover::FailoverTransport;
+use defi_hot_wallet::blockchain::rpc_limits::{
+    self, CallerClass, EndpointLimiter, RpcLimitError, DEFAULT_USER_WAIT,
+};
+use defi_hot_wallet::blockchain::BlockchainClient;
+use defi_hot_wallet::core::config::{BlockchainConfig, RpcEndpointConfig, RpcRateLimit};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::monitoring::WalletMetrics;
+
+fn per_second(requests_per_second: u32) -> RpcRateLimit {
+    RpcRateLimit { requests_per_second, burst: None, requests_per_day: None }
+}
+
+fn limiter(label: &str, limit: RpcRateLimit) -> Option<Arc<EndpointLimiter>> {
+    Some(Arc::new(EndpointLimiter::new(label, &limit)))
+}
+
+/// 总是返回同一区块号的节点
+fn node(block: u64, responses: usize) -> MockProvider {
+    let mock = MockProvider::new();
+    for _ in 0..responses {
+        mock.push(U64::from(block)).unwrap();
+    }
+    mock
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_user_call_waits_within_budget_then_fails_busy() {
+    let limiters = [limiter("primary", per_second(2))];
+    let budget = Duration::from_millis(600);
+    assert_eq!(rpc_limits::acquire(&limiters, budget).await, Ok(0));
+    assert_eq!(rpc_limits::acquire(&limiters, budget).await, Ok(0));
+
+    // 下一个令牌 500ms 后到，在预算内：等待
+    let start = Instant::now();
+    assert_eq!(rpc_limits::acquire(&limiters, budget).await, Ok(0));
+    assert_eq!(start.elapsed(), Duration::from_millis(500));
+
+    // 需要再等 500ms，超出 100ms 的预算：立即返回 NETWORK_BUSY
+    let start = Instant::now();
+    let busy = rpc_limits::acquire(&limiters, Duration::from_millis(100)).await;
+    assert_eq!(busy, Err(RpcLimitError::Busy("primary".to_string())));
+    assert_eq!(start.elapsed(), Duration::ZERO);
+    assert!(busy.unwrap_err().to_string().starts_with("NETWORK_BUSY"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_probe_is_skipped_without_tokens() {
+    let limiters = [limiter("primary", per_second(1))];
+    CallerClass::Probe
+        .scope(async {
+            assert_eq!(CallerClass::current(), CallerClass::Probe);
+            assert_eq!(rpc_limits::acquire(&limiters, DEFAULT_USER_WAIT).await, Ok(0));
+            let start = Instant::now();
+            let skipped = rpc_limits::acquire(&limiters, DEFAULT_USER_WAIT).await;
+            assert_eq!(skipped, Err(RpcLimitError::Skipped("primary".to_string())));
+            assert_eq!(start.elapsed(), Duration::ZERO);
+        })
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_background_waits_past_user_budget_until_cancelled() {
+    let limiters = [limiter("primary", per_second(1))];
+    rpc_limits::background(CancellationToken::new(), async {
+        assert_eq!(rpc_limits::acquire(&limiters, DEFAULT_USER_WAIT).await, Ok(0));
+        // 一秒远超user预算，后台照样等到令牌
+        let start = Instant::now();
+        assert_eq!(rpc_limits::acquire(&limiters, DEFAULT_USER_WAIT).await, Ok(0));
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    })
+    .await;
+
+    // 当天配额用完：下一个令牌要等 24h，取消后立即放弃
+    let daily = RpcRateLimit { requests_per_second: 1, burst: None, requests_per_day: Some(1) };
+    let limiters = [limiter("daily", daily)];
+    let cancel = CancellationToken::new();
+    tokio::spawn({
+        let cancel = cancel.clone();
+        async move {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            cancel.cancel();
+        }
+    });
+    let start = Instant::now();
+    let outcome = rpc_limits::background(cancel, async {
+        assert_eq!(rpc_limits::acquire(&limiters, DEFAULT_USER_WAIT).await, Ok(0));
+        rpc_limits::acquire(&limiters, DEFAULT_USER_WAIT).await
+    })
+    .await;
+    assert_eq!(outcome, Err(RpcLimitError::Cancelled("daily".to_string())));
+    assert_eq!(start.elapsed(), Duration::from_secs(10));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_background_job_yields_next_token_to_waiting_user() {
+    let limiters = Arc::new([limiter("primary", per_second(1))]);
+    let start = Instant::now();
+    assert_eq!(rpc_limits::acquire(&limiters[..], DEFAULT_USER_WAIT).await, Ok(0));
+
+    // 后台先排队（令牌 1s 后到）
+    let background = tokio::spawn({
+        let limiters = limiters.clone();
+        async move {
+            let acquired =
+                rpc_limits::background(CancellationToken::new(), rpc_limits::acquire(&limiters[..], DEFAULT_USER_WAIT))
+                    .await;
+            (acquired, start.elapsed())
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(600)).await;
+
+    // user后到，但拿到 1s 时的令牌
+    assert_eq!(rpc_limits::acquire(&limiters[..], Duration::from_secs(1)).await, Ok(0));
+    let user_elapsed = start.elapsed();
+    assert!(user_elapsed >= Duration::from_secs(1) && user_elapsed < Duration::from_millis(1010));
+
+    let (acquired, elapsed) = background.await.unwrap();
+    assert_eq!(acquired, Ok(0));
+    assert!(elapsed >= Duration::from_secs(2) && elapsed < Duration::from_millis(2100), "{:?}", elapsed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_failover_takes_load_when_primary_is_out_of_quota() {
+    let primary = node(100, 1);
+    let secondary = node(200, 2);
+    let transport = FailoverTransport::new("primary", primary.clone(), limiter("primary", per_second(1)))
+        .with_fallback("secondary", secondary.clone(), limiter("secondary", per_second(2)));
+    let client = EthereumClient::new_with_provider_and_chain(Provider::new(transport), "eth", 1);
+
+    assert_eq!(client.get_block_number().await.unwrap(), 100);
+    // 主节点令牌用完：直接走备用节点，不等待
+    let start = Instant::now();
+    assert_eq!(client.get_block_number().await.unwrap(), 200);
+    assert_eq!(client.get_block_number().await.unwrap(), 200);
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    // 两个节点都用完：user调用在预算内等不到，返回 NetworkBusy
+    let err = client.get_block_number().await.unwrap_err();
+    assert!(matches!(err, WalletError::NetworkBusy(ref msg) if msg.contains("primary")), "{}", err);
+    assert!(err.is_retryable());
+
+    // 回填后优先用主节点
+    tokio::time::advance(Duration::from_secs(1)).await;
+    primary.push(U64::from(100u64)).unwrap();
+    assert_eq!(client.get_block_number().await.unwrap(), 100);
+}
+
+#[tokio::test]
+async fn test_endpoint_calls_are_counted_per_network() {
+    let metrics = Arc::new(WalletMetrics::new().unwrap());
+    // 一个响应：第二次调用节点报错
+    let transport = FailoverTransport::new("primary", node(100, 1), None).with_metrics("polygon", metrics.clone());
+    let client = EthereumClient::new_with_provider_and_chain(Provider::new(transport), "polygon", 137);
+
+    assert_eq!(client.get_block_number().await.unwrap(), 100);
+    assert!(client.get_block_number().await.is_err());
+
+    let exported = metrics.export_metrics().unwrap();
+    assert!(exported.contains("blockchain_calls_by_network_total{network=\"polygon\"} 2"), "{}", exported);
+    assert!(exported.contains("blockchain_errors_by_network_total{network=\"polygon\"} 1"), "{}", exported);
+    assert!(exported.contains("network_latency_by_network_seconds_count{network=\"polygon\"} 2"), "{}", exported);
+    // 旧的无标签名称作为总和继续输出
+    assert!(exported.contains("blockchain_calls_total 2"), "{}", exported);
+}
+
+#[test]
+fn test_zero_rate_limit_is_rejected_and_valid_config_builds() {
+    let mut config = BlockchainConfig::default();
+    let eth = config.networks.get_mut("eth").unwrap();
+    eth.rate_limit = Some(per_second(10));
+    eth.fallback_endpoints = vec![RpcEndpointConfig {
+        url: "https://fallback.example.org/key".to_string(),
+        rate_limit: Some(RpcRateLimit { requests_per_second: 5, burst: Some(0), requests_per_day: None }),
+    }];
+    let err = config.validate().unwrap_err();
+    assert!(err.to_string().contains("blockchain.networks.eth.fallback_endpoints[0].rate_limit"), "{}", err);
+
+    config.networks.get_mut("eth").unwrap().fallback_endpoints[0].rate_limit = Some(per_second(5));
+    config.validate().unwrap();
+    let registry = ClientRegistry::from_config(&config);
+    assert!(registry.get("eth").is_ok());
+    let labels: Vec<String> = registry.rpc_limiters().utilization().into_keys().collect();
+    assert_eq!(labels, vec!["eth.llamarpc.com".to_string(), "fallback.example.org".to_string()]);
+}
@@ -0,0 +1,380 @@
+//! sandbox 克隆：新密钥与一致的address改写、审计日志清洗、目标库中不含源密钥字节、
+//! sandbox 标记阻止加载主网以及重复克隆的幂等性
+
+use axum_test::TestServer;
+use ethers::types::U256;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::core::address_book::AddressBookEntry;
+use defi_hot_wallet::core::clock::system_clock;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::core::ids::SequentialIds;
+use defi_hot_wallet::core::wallet_manager::{CreateWalletOptions, WalletManager};
+use defi_hot_wallet::ops::sandbox_clone::{CloneReport, SandboxClone};
+use defi_hot_wallet::storage::{
+    CloneSource, LedgerScope, NewAttestation, NewLedgerEntry, TransactionRecord, WalletStorage,
+    CLONED_ATTESTATION_REASON, DIRECTION_OUT, NATIVE_TOKEN, TX_ENTRY_INDEX,
+};
+
+const PASSWORD: &str = "Sandb0x!Staging#2024";
+const TX_HASH: &str = "0x5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a";
+
+fn url(path: &Path) -> String {
+    format!("sqlite://{}?mode=rwc", path.display())
+}
+
+fn config(database_url: &str) -> WalletConfig {
+    WalletConfig {
+        storage: StorageConfig {
+            database_url: database_url.to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    }
+}
+
+/// 没有 RPC 客户端：网络初始化降级为 needs_sync，不访问网络
+async fn create(storage: &WalletStorage, name: &str, networks: &[&str]) {
+    let manager = WalletManager::new(&config("sqlite::memory:")).await.unwrap();
+    let options = CreateWalletOptions {
+        password: PASSWORD.to_string(),
+        quantum_safe: false,
+        networks: networks.iter().map(|n| n.to_string()).collect(),
+        derivation_path: None,
+        passphrase: None,
+    };
+    manager.create_wallet_full(name, options, storage, &ClientRegistry::new()).await.unwrap();
+}
+
+async fn address(storage: &WalletStorage, wallet: &str, network: &str) -> String {
+    let networks = storage.wallet_networks(wallet).await.unwrap();
+    networks.into_iter().find(|n| n.network == network).unwrap().address
+}
+
+/// 源库：treasury（eth + polygon）向 payroll（eth）转账，外加审计、地址簿、证明、账本和审批阈值
+async fn seed_source(path: &Path) -> WalletStorage {
+    let source = WalletStorage::new_with_url(&url(path)).await.unwrap();
+    create(&source, "treasury", &["eth", "polygon"]).await;
+    create(&source, "payroll", &["eth"]).await;
+    let treasury = address(&source, "treasury", "eth").await;
+    let payroll = address(&source, "payroll", "eth").await;
+
+    let now = chrono::Utc::now();
+    source
+        .store_transaction(&TransactionRecord {
+            id: "tx-treasury-1".to_string(),
+            wallet_id: source.wallet_id("treasury").await.unwrap().unwrap(),
+            tx_hash: TX_HASH.to_string(),
+            network: "eth".to_string(),
+            from_address: treasury.clone(),
+            to_address: payroll.clone(),
+            amount: "1.5".to_string(),
+            fee: "0.0001".to_string(),
+            status: "confirmed".to_string(),
+            created_at: now,
+            confirmed_at: Some(now),
+            integrity_hash: String::new(),
+            fee_actual: None,
+            block_number: None,
+        })
+        .await
+        .unwrap();
+    source
+        .log_action(
+            "treasury",
+            "wallet.send",
+            &serde_json::json!({ "from": treasury, "to": payroll.to_lowercase() }).to_string(),
+            Some("203.0.113.7"),
+            Some("curl/8.4"),
+        )
+        .await
+        .unwrap();
+    source
+        .upsert_address_book(
+            "treasury",
+            &[AddressBookEntry {
+                network: "eth".to_string(),
+                address: payroll.clone(),
+                label: "payroll".to_string(),
+                tags: vec!["internal".to_string()],
+            }],
+        )
+        .await
+        .unwrap();
+    source
+        .record_attestation(&NewAttestation {
+            wallet_name: "treasury",
+            network: "eth",
+            scheme: "eip191",
+            address: &treasury,
+            challenge: "prove-control-2024",
+            signature: "0xsigned",
+            audience: None,
+            expires_at: None,
+        })
+        .await
+        .unwrap();
+    let scope = LedgerScope { wallet_name: "treasury", network: "eth", address: &treasury, source: "rpc" };
+    let entry = NewLedgerEntry {
+        tx_hash: TX_HASH.to_string(),
+        log_index: TX_ENTRY_INDEX,
+        block_number: 100,
+        direction: DIRECTION_OUT,
+        token: NATIVE_TOKEN.to_string(),
+        counterparty: Some(payroll.clone()),
+        amount: U256::from(1_500_000_000_000_000_000u64),
+        fee: U256::from(100_000_000_000_000u64),
+        success: true,
+        occurred_at: now.timestamp(),
+    };
+    source.record_onchain_transfers(&scope, &[entry]).await.unwrap();
+    source.set_review_threshold("treasury", Some("5"), "admin").await.unwrap();
+    source
+}
+
+async fn clone_into(source_path: &Path, target: &WalletStorage, wallets: Option<Vec<String>>) -> CloneReport {
+    let source = CloneSource::open(&url(source_path)).await.unwrap();
+    let manager = WalletManager::new(&config("sqlite::memory:")).await.unwrap();
+    let mut clone = SandboxClone::new(PASSWORD);
+    if let Some(wallets) = wallets {
+        clone = clone.with_wallets(wallets);
+    }
+    let report = clone.run(&source, target, &manager, &ClientRegistry::new()).await.unwrap();
+    source.close().await;
+    report
+}
+
+fn file_bytes(path: &Path) -> Vec<u8> {
+    let mut bytes = std::fs::read(path).unwrap();
+    let mut wal = path.as_os_str().to_owned();
+    wal.push("-wal");
+    if let Ok(extra) = std::fs::read(PathBuf::from(wal)) {
+        bytes.extend(extra);
+    }
+    bytes
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_clone_rewrites_addresses_consistently() {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = tempfile::tempdir().unwrap();
+    let source = seed_source(&dir.path().join("source.db")).await;
+    let target = WalletStorage::new_with_url(&url(&dir.path().join("sandbox.db"))).await.unwrap();
+
+    let report = clone_into(&dir.path().join("source.db"), &target, None).await;
+    assert!(target.is_sandbox().await.unwrap());
+    let names: Vec<&str> = report.wallets.iter().map(|w| w.name.as_str()).collect();
+    assert_eq!(names, ["payroll", "treasury"]);
+    assert!(report.wallets.iter().all(|w| w.created && w.unmapped_networks.is_empty()));
+    assert!(report.skipped_tables.contains(&"wallet_tokens".to_string()));
+
+    let old_treasury = address(&source, "treasury", "eth").await;
+    let old_payroll = address(&source, "payroll", "eth").await;
+    let treasury = address(&target, "treasury", "eth").await;
+    let payroll = address(&target, "payroll", "eth").await;
+    assert_ne!(treasury, old_treasury);
+    assert_ne!(payroll, old_payroll);
+    // 报告中的映射按 (network, 源address) 排序
+    assert_eq!(report.addresses.len(), 3);
+    let mut sorted = report.addresses.clone();
+    sorted.sort_by(|a, b| (&a.network, &a.source).cmp(&(&b.network, &b.source)));
+    assert_eq!(sorted, report.addresses);
+    let mapped = |network: &str, old: &str| {
+        report
+            .addresses
+            .iter()
+            .find(|r| r.network == network && r.source == old.to_lowercase())
+            .unwrap()
+            .sandbox
+            .clone()
+    };
+    assert_eq!(mapped("eth", &old_treasury), treasury);
+    assert_eq!(
+        mapped("polygon", &address(&source, "treasury", "polygon").await),
+        address(&target, "treasury", "polygon").await
+    );
+
+    // transaction两端都改写，完整性哈希重新计算（读取时校验）
+    let tx = target.transaction_by_hash(TX_HASH).await.unwrap().unwrap();
+    assert_eq!((tx.from_address.as_str(), tx.to_address.as_str()), (treasury.as_str(), payroll.as_str()));
+
+    let ledger = target.ledger_entries("treasury", "eth").await.unwrap();
+    assert_eq!(ledger.len(), 1);
+    assert_eq!(ledger[0].address, treasury);
+    assert_eq!(ledger[0].counterparty.as_deref(), Some(payroll.as_str()));
+
+    let book = target.address_book("treasury").await.unwrap();
+    assert_eq!(book[0].address, payroll);
+
+    // 证明由源密钥签名：地址改写并撤销
+    let attestations = target.attestations_for("treasury").await.unwrap();
+    assert_eq!(attestations[0].address, treasury);
+    assert_eq!(attestations[0].revoked_reason.as_deref(), Some(CLONED_ATTESTATION_REASON));
+
+    assert_eq!(target.review_threshold("treasury").await.unwrap().as_deref(), Some("5"));
+
+    // 审计日志：去掉 IP / UA，details 中的地址改写，MAC 在读取时validate
+    let logs = target.get_audit_logs(Some("treasury")).await.unwrap();
+    let send = logs.iter().find(|l| l.action == "wallet.send").unwrap();
+    assert_eq!((send.ip_address.as_deref(), send.user_agent.as_deref()), (None, None));
+    let details: Value = serde_json::from_str(send.details.as_deref().unwrap()).unwrap();
+    assert_eq!(details["from"], treasury.as_str());
+    assert_eq!(details["to"], payroll.as_str());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_target_holds_no_source_key_bytes() {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = tempfile::tempdir().unwrap();
+    let source = seed_source(&dir.path().join("source.db")).await;
+    let target_path = dir.path().join("sandbox.db");
+    let target = WalletStorage::new_with_url(&url(&target_path)).await.unwrap();
+    clone_into(&dir.path().join("source.db"), &target, None).await;
+    drop(target);
+
+    let bytes = file_bytes(&target_path);
+    for wallet in ["treasury", "payroll"] {
+        let (sealed, _) = source.load_wallet(wallet).await.unwrap();
+        assert!(!contains(&bytes, &sealed[..64]), "{}'s sealed key reached the sandbox", wallet);
+        let old = address(&source, wallet, "eth").await;
+        assert!(!contains(&bytes, old.as_bytes()), "{}'s source address reached the sandbox", wallet);
+        assert!(!contains(&bytes, old.to_lowercase().as_bytes()));
+    }
+    assert!(!contains(&bytes, b"203.0.113.7"));
+    assert!(!contains(&bytes, b"curl/8.4"));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_reclone_updates_instead_of_duplicating() {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = tempfile::tempdir().unwrap();
+    seed_source(&dir.path().join("source.db")).await;
+    let target = WalletStorage::new_with_url(&url(&dir.path().join("sandbox.db"))).await.unwrap();
+
+    let only_treasury = Some(vec!["treasury".to_string()]);
+    let first = clone_into(&dir.path().join("source.db"), &target, only_treasury.clone()).await;
+    let second = clone_into(&dir.path().join("source.db"), &target, only_treasury).await;
+    assert!(first.wallets[0].created);
+    assert!(!second.wallets[0].created);
+    // 沿用已有 sandbox wallet的address
+    assert_eq!(first.addresses, second.addresses);
+    assert_eq!(second.wallets[0].rows["audit_logs"], 0);
+
+    assert_eq!(target.list_wallets().await.unwrap().len(), 1);
+    let treasury_id = target.wallet_id("treasury").await.unwrap().unwrap();
+    assert_eq!(target.get_wallet_transactions(&treasury_id).await.unwrap().len(), 1);
+    assert_eq!(target.ledger_entries("treasury", "eth").await.unwrap().len(), 1);
+    let logs = target.get_audit_logs(Some("treasury")).await.unwrap();
+    assert_eq!(logs.iter().filter(|l| l.action == "wallet.send").count(), 1);
+
+    // 未克隆的 payroll 地址保持原样
+    let source = WalletStorage::new_with_url(&url(&dir.path().join("source.db"))).await.unwrap();
+    let tx = target.transaction_by_hash(TX_HASH).await.unwrap().unwrap();
+    assert_eq!(tx.to_address, address(&source, "payroll", "eth").await);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_clone_refuses_a_non_sandbox_target_with_wallets() {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = tempfile::tempdir().unwrap();
+    seed_source(&dir.path().join("source.db")).await;
+    let target = WalletStorage::new_with_url(&url(&dir.path().join("live.db"))).await.unwrap();
+    create(&target, "operations", &["eth"]).await;
+
+    let source = CloneSource::open(&url(&dir.path().join("source.db"))).await.unwrap();
+    let manager = WalletManager::new(&config("sqlite::memory:")).await.unwrap();
+    let err = SandboxClone::new(PASSWORD).run(&source, &target, &manager, &ClientRegistry::new()).await.err().unwrap();
+    assert!(err.to_string().contains("not a sandbox"), "{}", err);
+    assert!(!target.is_sandbox().await.unwrap());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_sandbox_flag_blocks_mainnet_networks() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", url(&dir.path().join("users.db")));
+    let database_url = url(&dir.path().join("sandbox.db"));
+    let storage = WalletStorage::new_with_url(&database_url).await.unwrap();
+    storage.mark_sandbox().await.unwrap();
+    drop(storage);
+
+    let config = config(&database_url);
+    assert_eq!(config.blockchain.networks["eth"].chain_id, 1);
+    let server = WalletServer::new_for_test_with_sources(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        None,
+        None,
+        system_clock(),
+        Arc::new(SequentialIds::new()),
+    )
+    .await
+    .unwrap();
+    assert!(server.sandbox);
+    let networks = &server.config.blockchain.networks;
+    assert!(!networks.contains_key("eth") && !networks.contains_key("polygon") && !networks.contains_key("bsc"));
+    assert!(networks.contains_key("sepolia"));
+    assert!(!server.wallet_manager.config.blockchain.networks.contains_key("eth"));
+
+    let app = TestServer::new(server.create_router().await).unwrap();
+    let health: Value = app.get("/health").await.json();
+    assert_eq!(health["sandbox"], true);
+}
+
+#[cfg(feature = "test-env")]
+#[tokio::test]
+#[serial_test::serial]
+async fn test_admin_endpoint_clones_into_a_sandbox_server() {
+    use defi_hot_wallet::security::SecretVec;
+
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", url(&dir.path().join("users.db")));
+    seed_source(&dir.path().join("source.db")).await;
+    let database_url = url(&dir.path().join("sandbox.db"));
+    let storage = WalletStorage::new_with_url(&database_url).await.unwrap();
+    storage.mark_sandbox().await.unwrap();
+    drop(storage);
+
+    let server = WalletServer::new_for_test_with_sources(
+        "127.0.0.1".to_string(),
+        0,
+        config(&database_url),
+        Some(SecretVec::new(b"sandbox-admin-key".to_vec())),
+        None,
+        system_clock(),
+        Arc::new(SequentialIds::new()),
+    )
+    .await
+    .unwrap();
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    let body = serde_json::json!({
+        "source_db": url(&dir.path().join("source.db")),
+        "wallets": ["payroll"],
+        "password": PASSWORD,
+    });
+    let res = app.post("/api/admin/sandbox-clone").json(&body).await;
+    assert_eq!(res.status_code(), axum::http::StatusCode::UNAUTHORIZED);
+
+    let res = app.post("/api/admin/sandbox-clone").add_header("X-API-KEY", "sandbox-admin-key").json(&body).await;
+    res.assert_status_ok();
+    let report: Value = res.json();
+    assert_eq!(report["wallets"][0]["name"], "payroll");
+    assert_eq!(storage.list_wallets().await.unwrap().len(), 1);
+}
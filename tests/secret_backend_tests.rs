@@ -0,0 +1,410 @@
+//! 密钥后端：文件 / Vault（mock HTTP，token 与 AppRole）、已安装后端优先于环境变量、
+//! 配置error、主密钥轮换（拒绝 / 重新封装）、API 密钥写回以及后端不可达时超期拒绝使用
+
+use base64::Engine;
+use httpmock::{Method, MockServer};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+use defi_hot_wallet::core::config::{
+    MasterKeyRotation, SecretBackendConfig, SecretSourceConfig, VaultAuthConfig, VaultSecretConfig,
+};
+use defi_hot_wallet::core::wallet::create::create_wallet;
+use defi_hot_wallet::crypto::quantum::QuantumSafeEncryption;
+use defi_hot_wallet::ops::envelope_rotation::EnvelopeRotation;
+use defi_hot_wallet::ops::secret_refresh::{RefreshOutcome, SecretRefresher};
+use defi_hot_wallet::security::env_manager::secret_backend::{
+    self, backend_from_config, SecretBackendError, SecretStore, API_KEY, API_KEY_SECONDARY, MASTER_KEY,
+};
+use defi_hot_wallet::security::env_manager::secure_env;
+use defi_hot_wallet::security::{ApiKeys, SecretVec};
+use defi_hot_wallet::storage::{WalletStorage, WalletStorageTrait};
+
+const OLD: [u8; 32] = [7; 32];
+const NEW: [u8; 32] = [9; 32];
+const TOKEN_ENV: &str = "SECRET_TEST_VAULT_TOKEN";
+const SECRET_PATH: &str = "/v1/secret/data/hot-wallet";
+
+fn b64(key: [u8; 32]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(key)
+}
+
+/// 测试结束时卸载进程级密钥缓存
+struct Installed;
+
+impl Installed {
+    fn new(store: SecretStore) -> (Self, Arc<SecretStore>) {
+        let store = Arc::new(store);
+        secret_backend::install(store.clone());
+        (Installed, store)
+    }
+}
+
+impl Drop for Installed {
+    fn drop(&mut self) {
+        secret_backend::uninstall();
+    }
+}
+
+fn write_secret(dir: &Path, name: &str, value: &str, mode: u32) {
+    let path = dir.join(name);
+    std::fs::write(&path, value).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+}
+
+fn vault_config(server: &MockServer, auth: VaultAuthConfig) -> SecretSourceConfig {
+    SecretSourceConfig::Vault(VaultSecretConfig {
+        address: server.base_url(),
+        mount: "secret".to_string(),
+        path: "hot-wallet".to_string(),
+        namespace: Some("ops".to_string()),
+        auth,
+        fields: HashMap::from([(API_KEY.to_string(), "admin_api_key".to_string())]),
+        ca_cert: None,
+        timeout_secs: 2,
+    })
+}
+
+fn token_auth() -> VaultAuthConfig {
+    VaultAuthConfig::Token { token_env: TOKEN_ENV.to_string() }
+}
+
+/// `token` 读取 hot-wallet 时的 KV v2 响应
+fn mock_secret<'a>(server: &'a MockServer, token: &str, fields: Value) -> httpmock::Mock<'a> {
+    let body = json!({ "data": { "data": fields, "metadata": { "version": 1 } } });
+    server.mock(|when, then| {
+        when.method(Method::GET).path(SECRET_PATH).header("X-Vault-Token", token).header("X-Vault-Namespace", "ops");
+        then.status(200).header("content-type", "application/json").json_body(body);
+    })
+}
+
+async fn vault_store(server: &MockServer, max_staleness: Option<Duration>) -> SecretStore {
+    let backend = backend_from_config(&vault_config(server, token_auth())).unwrap();
+    SecretStore::open(backend, max_staleness).await.unwrap()
+}
+
+fn encoded_master_key() -> String {
+    secret_backend::master_key_encoded().unwrap().to_string()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_file_backend_reads_trimmed_values() {
+    let dir = tempfile::tempdir().unwrap();
+    write_secret(dir.path(), MASTER_KEY, &format!("{}\n", b64(OLD)), 0o600);
+    let backend = backend_from_config(&SecretSourceConfig::File { directory: dir.path().to_path_buf() }).unwrap();
+    assert_eq!(backend.kind(), "file");
+
+    let key = backend.fetch(MASTER_KEY).await.unwrap().unwrap();
+    assert_eq!(key.as_slice(), b64(OLD).as_bytes());
+    // 可选的密钥没有文件
+    assert!(backend.fetch(API_KEY).await.unwrap().is_none());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+#[serial_test::serial]
+async fn test_file_backend_refuses_files_readable_by_others() {
+    let dir = tempfile::tempdir().unwrap();
+    write_secret(dir.path(), MASTER_KEY, &b64(OLD), 0o644);
+    let backend = backend_from_config(&SecretSourceConfig::File { directory: dir.path().to_path_buf() }).unwrap();
+
+    let err = SecretStore::open(backend, None).await.err().unwrap();
+    assert!(matches!(err, SecretBackendError::Misconfigured { backend: "file", .. }), "{:?}", err);
+    assert!(err.to_string().contains("chmod 600"), "{}", err);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_open_refuses_a_backend_without_the_master_key() {
+    let dir = tempfile::tempdir().unwrap();
+    write_secret(dir.path(), API_KEY, "only-the-api-key-is-here-0123456789", 0o600);
+    let backend = backend_from_config(&SecretSourceConfig::File { directory: dir.path().to_path_buf() }).unwrap();
+
+    let err = SecretStore::open(backend, None).await.err().unwrap();
+    assert!(matches!(err, SecretBackendError::NotSet { ref name, backend: "file" } if name == MASTER_KEY));
+    assert!(err.to_string().contains("not set"));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_installed_backend_takes_precedence_over_the_environment() {
+    std::env::set_var(MASTER_KEY, b64(OLD));
+    std::env::set_var(API_KEY, "env-api-key");
+    let dir = tempfile::tempdir().unwrap();
+    write_secret(dir.path(), MASTER_KEY, &b64(NEW), 0o600);
+    write_secret(dir.path(), API_KEY, "file-api-key", 0o600);
+    let backend = backend_from_config(&SecretSourceConfig::File { directory: dir.path().to_path_buf() }).unwrap();
+    let store = SecretStore::open(backend, None).await.unwrap();
+
+    {
+        let _installed = Installed::new(store);
+        assert_eq!(encoded_master_key(), b64(NEW));
+        assert_eq!(secure_env::get_wallet_enc_key().unwrap().as_slice(), &NEW);
+        assert_eq!(secure_env::get_api_key().unwrap().as_slice(), b"file-api-key");
+    }
+
+    // 卸载后回到环境变量
+    assert_eq!(encoded_master_key(), b64(OLD));
+    assert_eq!(secure_env::get_api_key().unwrap().as_slice(), b"env-api-key");
+    std::env::remove_var(API_KEY);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_misconfigured_backends_are_reported() {
+    let server = MockServer::start_async().await;
+    std::env::remove_var(TOKEN_ENV);
+    let err = backend_from_config(&vault_config(&server, token_auth())).err().unwrap();
+    assert!(matches!(err, SecretBackendError::Misconfigured { backend: "vault", .. }));
+    assert!(err.to_string().contains(TOKEN_ENV), "{}", err);
+
+    let approle = VaultAuthConfig::AppRole {
+        mount: "approle".to_string(),
+        role_id_env: "SECRET_TEST_ROLE_ID".to_string(),
+        secret_id_env: "SECRET_TEST_SECRET_ID".to_string(),
+    };
+    std::env::set_var("SECRET_TEST_ROLE_ID", "role");
+    std::env::remove_var("SECRET_TEST_SECRET_ID");
+    let err = backend_from_config(&vault_config(&server, approle)).err().unwrap();
+    assert!(err.to_string().contains("SECRET_TEST_SECRET_ID"), "{}", err);
+
+    #[cfg(not(feature = "os-keychain"))]
+    {
+        let keychain = SecretSourceConfig::Keychain { service: "defi-hot-wallet".to_string(), entries: HashMap::new() };
+        let err = backend_from_config(&keychain).err().unwrap();
+        assert!(matches!(err, SecretBackendError::Misconfigured { backend: "keychain", .. }));
+        assert!(err.to_string().contains("os-keychain"), "{}", err);
+    }
+}
+
+#[test]
+fn test_config_section_parses() {
+    let config: SecretBackendConfig = toml::from_str(
+        r#"
+        refresh_interval_secs = 60
+        on_rotation = "rewrap"
+
+        [source]
+        type = "vault"
+        address = "https://vault.internal:8200"
+        path = "hot-wallet"
+
+        [source.auth]
+        method = "approle"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.refresh_interval_secs, 60);
+    assert_eq!(config.max_staleness_secs, SecretBackendConfig::default().max_staleness_secs);
+    assert_eq!(config.on_rotation, MasterKeyRotation::Rewrap);
+    let SecretSourceConfig::Vault(vault) = config.source else { panic!("expected vault") };
+    assert_eq!(vault.mount, "secret");
+    assert_eq!(
+        vault.auth,
+        VaultAuthConfig::AppRole {
+            mount: "approle".to_string(),
+            role_id_env: "VAULT_ROLE_ID".to_string(),
+            secret_id_env: "VAULT_SECRET_ID".to_string(),
+        }
+    );
+
+    assert_eq!(SecretBackendConfig::default().source, SecretSourceConfig::Env);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_vault_token_auth_reads_kv_v2_fields() {
+    let server = MockServer::start_async().await;
+    std::env::set_var(TOKEN_ENV, "root-token");
+    let read = mock_secret(&server, "root-token", json!({ "wallet_enc_key": b64(OLD), "admin_api_key": "vault-api" }));
+
+    let backend = backend_from_config(&vault_config(&server, token_auth())).unwrap();
+    assert_eq!(backend.kind(), "vault");
+    assert_eq!(backend.fetch(MASTER_KEY).await.unwrap().unwrap().as_slice(), b64(OLD).as_bytes());
+    // `fields` 把 API_KEY 映射到 admin_api_key
+    assert_eq!(backend.fetch(API_KEY).await.unwrap().unwrap().as_slice(), b"vault-api");
+    assert!(backend.fetch("UNLISTED").await.unwrap().is_none());
+    assert_eq!(read.hits(), 3);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_vault_store_writes_a_new_version_keeping_other_fields() {
+    let server = MockServer::start_async().await;
+    std::env::set_var(TOKEN_ENV, "root-token");
+    mock_secret(&server, "root-token", json!({ "wallet_enc_key": b64(OLD), "admin_api_key": "vault-api" }));
+    // check-and-set 针对读到的版本；主密钥原样写回
+    let write = server.mock(|when, then| {
+        when.method(Method::POST).path(SECRET_PATH).header("X-Vault-Token", "root-token").json_body(json!({
+            "options": { "cas": 1 },
+            "data": { "wallet_enc_key": b64(OLD), "admin_api_key": "vault-api", "api_key_secondary": "rotated" },
+        }));
+        then.status(200).header("content-type", "application/json").json_body(json!({ "data": { "version": 2 } }));
+    });
+
+    let store = vault_store(&server, None).await;
+    let keys = ApiKeys {
+        primary: SecretVec::new(b"vault-api".to_vec()),
+        secondary: Some(SecretVec::new(b"rotated".to_vec())),
+    };
+    store.store_api_keys(&keys).await.unwrap();
+    write.assert();
+    assert_eq!(store.get(API_KEY_SECONDARY).unwrap().unwrap().as_slice(), b"rotated");
+
+    // 环境变量后端只读
+    let err = backend_from_config(&SecretSourceConfig::Env).unwrap().store(&[(API_KEY, &keys.primary)]).await;
+    assert!(matches!(err, Err(SecretBackendError::Misconfigured { backend: "env", .. })), "{:?}", err);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_vault_approle_logs_in_again_when_the_token_is_refused() {
+    let server = MockServer::start_async().await;
+    std::env::set_var("SECRET_TEST_ROLE_ID", "role-1");
+    std::env::set_var("SECRET_TEST_SECRET_ID", "secret-1");
+    let approle = VaultAuthConfig::AppRole {
+        mount: "approle".to_string(),
+        role_id_env: "SECRET_TEST_ROLE_ID".to_string(),
+        secret_id_env: "SECRET_TEST_SECRET_ID".to_string(),
+    };
+    let login = |token: &str| {
+        let token = token.to_string();
+        server.mock(move |when, then| {
+            when.method(Method::POST)
+                .path("/v1/auth/approle/login")
+                .json_body(json!({ "role_id": "role-1", "secret_id": "secret-1" }));
+            then.status(200).json_body(json!({ "auth": { "client_token": token, "lease_duration": 60 } }));
+        })
+    };
+    let fields = json!({ "wallet_enc_key": b64(OLD) });
+
+    let mut first_login = login("token-1");
+    let mut first_read = mock_secret(&server, "token-1", fields.clone());
+    let backend = backend_from_config(&vault_config(&server, approle)).unwrap();
+    backend.fetch(MASTER_KEY).await.unwrap().unwrap();
+    backend.fetch(MASTER_KEY).await.unwrap().unwrap();
+    // token 被缓存：只登录一次
+    assert_eq!(first_login.hits(), 1);
+    assert_eq!(first_read.hits(), 2);
+
+    // token 过期：Vault 以 403 拒绝，重新登录后拿到新 token
+    first_login.delete();
+    first_read.delete();
+    let expired = server.mock(|when, then| {
+        when.method(Method::GET).path(SECRET_PATH).header("X-Vault-Token", "token-1");
+        then.status(403).json_body(json!({ "errors": ["permission denied"] }));
+    });
+    let second_login = login("token-2");
+    let second_read = mock_secret(&server, "token-2", fields);
+    backend.fetch(MASTER_KEY).await.unwrap().unwrap();
+    assert_eq!((expired.hits(), second_login.hits(), second_read.hits()), (1, 1, 1));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_unreachable_backend_makes_the_master_key_stale() {
+    let server = MockServer::start_async().await;
+    std::env::set_var(TOKEN_ENV, "root-token");
+    let mut secret = mock_secret(&server, "root-token", json!({ "wallet_enc_key": b64(OLD) }));
+    let store = vault_store(&server, Some(Duration::from_millis(300))).await;
+    let (_installed, store) = Installed::new(store);
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display());
+    let storage = Arc::new(WalletStorage::new_with_url(&url).await.unwrap());
+    let refresher = SecretRefresher::new(store, storage, Duration::from_secs(60), MasterKeyRotation::Refuse);
+
+    assert_eq!(refresher.run_once().await, RefreshOutcome::Unchanged);
+    assert_eq!(encoded_master_key(), b64(OLD));
+    assert!(secret_backend::ensure_master_key().is_ok());
+
+    // Vault 下线：刷新失败，缓存的密钥在有效期内仍可用
+    // （池化的 MockServer 在 drop 后仍会以 404 应答，等同于“secret 不存在”）
+    secret.delete();
+    server.mock(|when, then| {
+        when.method(Method::GET).path(SECRET_PATH);
+        then.status(503);
+    });
+    assert!(matches!(refresher.run_once().await, RefreshOutcome::Failed(_)));
+    assert_eq!(encoded_master_key(), b64(OLD));
+
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    let err = secret_backend::master_key_encoded().err().unwrap();
+    assert!(matches!(err, SecretBackendError::Stale { backend: "vault", .. }), "{:?}", err);
+    assert!(secret_backend::ensure_master_key().is_err());
+    assert!(secure_env::get_wallet_enc_key().is_err());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_rotation_is_refused_by_default() {
+    let server = MockServer::start_async().await;
+    std::env::set_var(TOKEN_ENV, "root-token");
+    let mut old = mock_secret(&server, "root-token", json!({ "wallet_enc_key": b64(OLD) }));
+    let store = vault_store(&server, None).await;
+    let (_installed, store) = Installed::new(store);
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display());
+    let storage = Arc::new(WalletStorage::new_with_url(&url).await.unwrap());
+    let refresher = SecretRefresher::new(store, storage, Duration::from_secs(60), MasterKeyRotation::Refuse);
+
+    old.delete();
+    let mut new = mock_secret(&server, "root-token", json!({ "wallet_enc_key": b64(NEW) }));
+    let RefreshOutcome::Refused(reason) = refresher.run_once().await else { panic!("rotation was not refused") };
+    assert!(reason.contains("rotate-kek"), "{}", reason);
+    let err = secret_backend::master_key_encoded().err().unwrap();
+    assert!(matches!(err, SecretBackendError::RotationPending { .. }), "{:?}", err);
+    assert!(secret_backend::ensure_master_key().is_err());
+
+    // 后端改回原来的密钥后恢复
+    new.delete();
+    mock_secret(&server, "root-token", json!({ "wallet_enc_key": b64(OLD) }));
+    assert_eq!(refresher.run_once().await, RefreshOutcome::Unchanged);
+    assert_eq!(encoded_master_key(), b64(OLD));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_rotation_rewraps_envelopes_once_across_instances() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display());
+    let storage = Arc::new(WalletStorage::new_with_url(&url).await.unwrap());
+    // 在安装后端之前用旧密钥创建wallet
+    std::env::set_var(MASTER_KEY, b64(OLD));
+    let dyn_storage: Arc<dyn WalletStorageTrait + Send + Sync> = storage.clone();
+    create_wallet(&dyn_storage, &QuantumSafeEncryption::new().unwrap(), "rotating", false).await.unwrap();
+
+    let server = MockServer::start_async().await;
+    std::env::set_var(TOKEN_ENV, "root-token");
+    let mut old = mock_secret(&server, "root-token", json!({ "wallet_enc_key": b64(OLD) }));
+    // 两个共享同一数据库的实例
+    let (_installed, store) = Installed::new(vault_store(&server, None).await);
+    let peer = Arc::new(vault_store(&server, None).await);
+    let refresher = SecretRefresher::new(store, storage.clone(), Duration::from_secs(60), MasterKeyRotation::Rewrap);
+    let peer_refresher =
+        SecretRefresher::new(peer, storage.clone(), Duration::from_secs(60), MasterKeyRotation::Rewrap);
+
+    old.delete();
+    mock_secret(&server, "root-token", json!({ "wallet_enc_key": b64(NEW) }));
+    let RefreshOutcome::Rewrapped { wallets, .. } = refresher.run_once().await else { panic!("not re-wrapped") };
+    assert_eq!(wallets, 1);
+    assert_eq!(encoded_master_key(), b64(NEW));
+    assert_eq!(peer_refresher.run_once().await, RefreshOutcome::AlreadyRewrapped);
+
+    // 信封现在只能用新密钥打开
+    let check = EnvelopeRotation::new(Zeroizing::new(NEW), Zeroizing::new(NEW)).run(&storage, true).await.unwrap();
+    assert_eq!((check.totals.wallets, check.totals.failed), (1, 0));
+    let stale = EnvelopeRotation::new(Zeroizing::new(OLD), Zeroizing::new(NEW)).run(&storage, true).await.unwrap();
+    assert_eq!(stale.totals.decrypt_failed, 1);
+    std::env::remove_var(MASTER_KEY);
+}
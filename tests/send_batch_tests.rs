@@ -0,0 +1,220 @@
+//! 批量发送：通过check的各笔占用一段连续 nonce 依次广播，单笔失败不影响其余各笔
+//! （`fail_fast` 时整批不发）；已分配 nonce 的一笔失败后停止并归还未用的 nonce
+
+mod util;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, H256};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::monitoring::WalletMetrics;
+use defi_hot_wallet::storage::WalletStorage;
+
+const API_KEY: &str = "send-batch-admin-key";
+const SESSION: &str = "send-batch-session";
+const WALLET: &str = "payroll";
+const PASSWORD: &str = "Payr0ll!Vault#2024";
+const ALICE: &str = "0x000000000000000000000000000000000000dead";
+const BOB: &str = "0x000000000000000000000000000000000000beef";
+
+/// 节点：保留调用方给定的 nonce，记录每次 prepare 的 nonce
+#[derive(Default)]
+struct MockChain {
+    /// 账户的 pending transaction数
+    pending: Mutex<u64>,
+    prepared: Mutex<Vec<u64>>,
+    /// 拒绝这个 nonce 的transaction
+    reject_nonce: Mutex<Option<u64>>,
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let nonce = match tx.nonce() {
+            Some(nonce) => nonce.as_u64(),
+            None => *self.pending.lock().unwrap(),
+        };
+        if *self.reject_nonce.lock().unwrap() == Some(nonce) {
+            return Err(WalletError::NetworkError("nonce too low".to_string()));
+        }
+        self.prepared.lock().unwrap().push(nonce);
+        tx.set_nonce(nonce);
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_count(&self, _network: &str, _address: Address) -> Result<u64, WalletError> {
+        Ok(*self.pending.lock().unwrap())
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    chain: Arc<MockChain>,
+    storage: Arc<WalletStorage>,
+    metrics: Arc<WalletMetrics>,
+    _dir: tempfile::TempDir,
+}
+
+async fn build(pending: u64) -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = Arc::new(MockChain { pending: Mutex::new(pending), ..Default::default() });
+    let server =
+        util::test_server(dir.path(), util::memory_config(), Some(API_KEY)).await.with_broadcast_chain(chain.clone());
+
+    let user_id = util::sign_in(&server, "payroll@example.com", SESSION).await;
+    util::create_wallet(&server, WALLET, PASSWORD).await;
+    let address = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+    server.user_db.link_wallet(&user_id, WALLET, &format!("{:#x}", address), None).await.unwrap();
+
+    let storage = server.storage.clone();
+    let metrics = server.metrics.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, chain, storage, metrics, _dir: dir }
+}
+
+fn transfer(to: &str, amount: &str) -> Value {
+    json!({ "to": to, "amount": amount, "network": "eth", "password": PASSWORD })
+}
+
+impl Harness {
+    async fn send_batch(&self, body: Value) -> axum_test::TestResponse {
+        self.app
+            .post(&format!("/api/wallets/{}/send_batch", WALLET))
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .json(&body)
+            .await
+    }
+
+    fn prepared(&self) -> Vec<u64> {
+        self.chain.prepared.lock().unwrap().clone()
+    }
+}
+
+fn statuses(body: &Value) -> Vec<&str> {
+    body["results"].as_array().unwrap().iter().map(|r| r["status"].as_str().unwrap()).collect()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_batch_sends_in_order_on_consecutive_nonces() {
+    let h = build(4).await;
+    let res = h
+        .send_batch(json!({ "transactions": [transfer(ALICE, "0.1"), transfer(BOB, "0.2"), transfer(ALICE, "0.3")] }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["network"], "eth");
+    assert_eq!(statuses(&body), ["sent", "sent", "sent"]);
+    assert!(body.get("first_failure").is_none());
+    let nonces: Vec<u64> = body["results"].as_array().unwrap().iter().map(|r| r["nonce"].as_u64().unwrap()).collect();
+    assert_eq!(nonces, [4, 5, 6]);
+    assert_eq!(h.prepared(), [4, 5, 6]);
+
+    // 每笔都是一条普通的发送记录
+    for result in body["results"].as_array().unwrap() {
+        let tx_hash = result["tx_hash"].as_str().unwrap();
+        assert!(h.storage.transaction_by_hash(tx_hash).await.unwrap().is_some());
+    }
+    assert_eq!(h.metrics.transactions_sent.get(), 3.0);
+    assert_eq!(h.metrics.transactions_failed.get(), 0.0);
+
+    // 下一批接着上一批的区间
+    let res = h.send_batch(json!({ "transactions": [transfer(BOB, "0.4")] })).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["results"][0]["nonce"], 7);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_failed_item_does_not_abort_the_batch_unless_fail_fast() {
+    let h = build(0).await;
+    let items = json!([transfer(ALICE, "0.1"), transfer(ALICE, "0.1"), transfer(BOB, "0.2")]);
+
+    let res = h.send_batch(json!({ "transactions": items.clone(), "fail_fast": true })).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(statuses(&body), ["skipped", "failed", "skipped"]);
+    assert_eq!(body["first_failure"], 1);
+    assert_eq!(body["results"][1]["code"], "DUPLICATE_TRANSACTION_SUSPECTED");
+    assert_eq!(body["results"][0]["code"], "BATCH_ABORTED");
+    assert!(h.prepared().is_empty());
+
+    // 默认只跳过重复的一笔，其余各笔的 nonce 仍然连续
+    let res = h.send_batch(json!({ "transactions": items })).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(statuses(&body), ["sent", "failed", "sent"]);
+    assert_eq!(body["first_failure"], 1);
+    assert_eq!(body["results"][0]["nonce"], 0);
+    assert!(body["results"][1].get("nonce").is_none());
+    assert_eq!(body["results"][2]["nonce"], 1);
+    assert_eq!(h.metrics.transactions_sent.get(), 2.0);
+    assert_eq!(h.metrics.transactions_failed.get(), 2.0);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_failure_after_nonce_assignment_stops_and_releases_the_rest() {
+    let h = build(0).await;
+    *h.chain.reject_nonce.lock().unwrap() = Some(1);
+    let res = h
+        .send_batch(json!({ "transactions": [transfer(ALICE, "0.1"), transfer(BOB, "0.2"), transfer(ALICE, "0.3")] }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(statuses(&body), ["sent", "failed", "skipped"]);
+    assert_eq!(body["first_failure"], 1);
+    assert_eq!(body["results"][1]["nonce"], 1);
+    assert_eq!(body["results"][2]["code"], "BATCH_ABORTED");
+
+    // 未用的 nonce 已归还：下一批从失败的那个 nonce 开始
+    *h.chain.reject_nonce.lock().unwrap() = None;
+    let res = h.send_batch(json!({ "transactions": [transfer(BOB, "0.5")] })).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["results"][0]["nonce"], 1);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_batch_is_checked_as_a_whole() {
+    let h = build(0).await;
+
+    // 单笔都低于审查阈值，合计超过时整批拒绝
+    h.storage.set_review_threshold(WALLET, Some("0.5"), "admin").await.unwrap();
+    let res = h.send_batch(json!({ "transactions": [transfer(ALICE, "0.3"), transfer(BOB, "0.3")] })).await;
+    res.assert_status_forbidden();
+    assert_eq!(res.json::<Value>()["error"]["code"], "BATCH_REQUIRES_REVIEW");
+    assert!(h.prepared().is_empty());
+
+    let mut other_network = transfer(BOB, "0.1");
+    other_network["network"] = json!("polygon");
+    let res = h.send_batch(json!({ "transactions": [transfer(ALICE, "0.1"), other_network] })).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_BATCH");
+
+    let signed = json!({ "to": ALICE, "amount": "0.1", "network": "eth", "signed_tx": "0x02f8" });
+    let res = h.send_batch(json!({ "transactions": [signed] })).await;
+    res.assert_status_bad_request();
+    assert!(res.json::<Value>()["error"]["message"].as_str().unwrap().contains("transactions[0]"));
+
+    let res = h.send_batch(json!({ "transactions": [] })).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "MISSING_PARAMETER");
+}
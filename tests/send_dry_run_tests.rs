@@ -0,0 +1,184 @@
+//! `dry_run` 发送：经 MockProvider 估算 gas、gas price 与 nonce，不sign、不广播、不占用 nonce；
+//! 发送前的check照常执行，eth_estimateGas revert 时返回 422
+
+mod util;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::providers::{JsonRpcError, MockProvider, MockResponse, Provider};
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionRequest, H256, U256};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+
+const API_KEY: &str = "dry-run-admin-key";
+const SESSION: &str = "dry-run-session";
+const WALLET: &str = "estimator";
+const PASSWORD: &str = "Dry!Run#Estimate2024";
+const ALICE: &str = "0x000000000000000000000000000000000000dead";
+const GWEI: u64 = 1_000_000_000;
+
+/// 广播用的节点：记录分配过的 nonce 与广播次数，dry run 不应触及
+#[derive(Default)]
+struct MockChain {
+    prepared: Mutex<usize>,
+    broadcast: Mutex<usize>,
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        *self.prepared.lock().unwrap() += 1;
+        tx.set_nonce(0u64);
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3 * GWEI);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        *self.broadcast.lock().unwrap() += 1;
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_count(&self, _network: &str, _address: Address) -> Result<u64, WalletError> {
+        Ok(0)
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    chain: Arc<MockChain>,
+    mock: MockProvider,
+    address: Address,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = Arc::new(MockChain::default());
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    let client = EthereumClient::new_with_provider_and_chain(provider, "eth", 1);
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY))
+        .await
+        .with_broadcast_chain(chain.clone())
+        .with_chain_clients(ClientRegistry::new().with_client("eth", Arc::new(client)));
+
+    let user_id = util::sign_in(&server, "estimator@example.com", SESSION).await;
+    util::create_wallet(&server, WALLET, PASSWORD).await;
+    let address = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+    server.user_db.link_wallet(&user_id, WALLET, &format!("{:#x}", address), None).await.unwrap();
+
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, chain, mock, address, _dir: dir }
+}
+
+impl Harness {
+    async fn send(&self, body: Value) -> axum_test::TestResponse {
+        self.app
+            .post(&format!("/api/wallets/{}/send", WALLET))
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .json(&body)
+            .await
+    }
+
+    fn untouched(&self) -> bool {
+        *self.chain.prepared.lock().unwrap() == 0 && *self.chain.broadcast.lock().unwrap() == 0
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_dry_run_estimates_without_sending() {
+    let h = build().await;
+    // 依次为 eth_gasPrice、eth_estimateGas、pending nonce；MockProvider 后进先出
+    h.mock.push(U256::from(12u64)).unwrap();
+    h.mock.push(U256::from(21_000u64)).unwrap();
+    h.mock.push(U256::from(20 * GWEI)).unwrap();
+
+    let res = h
+        .send(json!({ "to": ALICE, "amount": "0.5", "network": "eth", "password": PASSWORD, "dry_run": true }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["dry_run"], true);
+    assert_eq!(body["from_address"], format!("{:#x}", h.address));
+    assert_eq!(body["estimated_gas"], "21000");
+    assert_eq!(body["gas_price"], (20 * GWEI).to_string());
+    assert_eq!(body["total_fee_wei"], "420000000000000");
+    assert_eq!(body["total_cost_wei"], "500420000000000000");
+    assert_eq!(body["nonce_to_use"], 12);
+    assert!(h.untouched());
+
+    // 以wallet address估算，不带 nonce 与签名
+    let transfer: TypedTransaction = TransactionRequest::new()
+        .from(h.address)
+        .to(ALICE.parse::<Address>().unwrap())
+        .value(U256::exp10(17) * 5)
+        .into();
+    h.mock.assert_request("eth_gasPrice", ()).unwrap();
+    h.mock.assert_request("eth_estimateGas", [transfer]).unwrap();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_dry_run_reports_estimate_gas_revert() {
+    let h = build().await;
+    h.mock.push_response(MockResponse::Error(JsonRpcError {
+        code: 3,
+        message: "execution reverted".to_string(),
+        data: None,
+    }));
+    h.mock.push(U256::from(20 * GWEI)).unwrap();
+
+    let res = h
+        .send(json!({ "to": ALICE, "amount": "0.5", "network": "eth", "password": PASSWORD, "dry_run": true }))
+        .await;
+    res.assert_status(axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(res.json::<Value>()["error"]["code"], "GAS_ESTIMATION_REVERTED");
+    assert!(h.untouched());
+
+    // 节点无答复是 502，可重试
+    let res = h
+        .send(json!({ "to": ALICE, "amount": "0.5", "network": "eth", "password": PASSWORD, "dry_run": true }))
+        .await;
+    res.assert_status(axum::http::StatusCode::BAD_GATEWAY);
+    assert_eq!(res.json::<Value>()["error"]["code"], "GAS_ESTIMATION_FAILED");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_dry_run_runs_the_send_checks() {
+    let h = build().await;
+
+    let res = h
+        .send(json!({ "to": ALICE, "amount": "0.5", "network": "eth", "signed_tx": "0x02", "dry_run": true }))
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "DRY_RUN_UNSUPPORTED");
+
+    // 支出上限在估算前check，节点不会被询问
+    h.app
+        .put(&format!("/api/wallets/{}/limits", WALLET))
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "network": "eth", "max_per_tx": "0.1" }))
+        .await
+        .assert_status_ok();
+    let res = h
+        .send(json!({ "to": ALICE, "amount": "0.5", "network": "eth", "password": PASSWORD, "dry_run": true }))
+        .await;
+    res.assert_status_forbidden();
+    assert_eq!(res.json::<Value>()["error"]["code"], "SPENDING_LIMIT_EXCEEDED");
+    assert!(h.mock.assert_request("eth_gasPrice", ()).is_err());
+    assert!(h.untouched());
+}
@@ -0,0 +1,152 @@
+//! Shamir 份额备份/恢复集成测试：2-of-3、3-of-5 经 HTTP 往返，份额不足、重复、被改动与参数越界时的error码
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::WalletStorage;
+use std::sync::Arc;
+
+const API_KEY: &str = "shamir-backup-test-api-key-0123456789";
+const PASSWORD: &str = "Sh4redVault!";
+
+struct Harness {
+    app: TestServer,
+    storage: Arc<WalletStorage>,
+    address: String,
+    _dir: TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = TempDir::new().unwrap();
+    let mut config = WalletConfig {
+        storage: StorageConfig {
+            database_url: format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display()),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    config.security.pbkdf2_iterations = 1_000;
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    server.wallet_manager.create_wallet("vault", PASSWORD, false).await.unwrap();
+    let address = server.wallet_manager.get_ethereum_address_from_master_key("vault", PASSWORD).await.unwrap();
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, storage, address, _dir: dir }
+}
+
+impl Harness {
+    async fn backup(&self, threshold: u8, total: u8) -> axum_test::TestResponse {
+        self.app
+            .post("/api/wallets/vault/backup/shamir")
+            .add_header("X-API-KEY", API_KEY)
+            .json(&json!({ "password": PASSWORD, "threshold": threshold, "total": total }))
+            .await
+    }
+
+    async fn shares(&self, threshold: u8, total: u8) -> Vec<Value> {
+        let res = self.backup(threshold, total).await;
+        res.assert_status_ok();
+        let body: Value = res.json();
+        assert_eq!(body["address"], self.address);
+        body["shares"].as_array().unwrap().clone()
+    }
+
+    async fn restore(&self, name: &str, shares: &[Value]) -> axum_test::TestResponse {
+        self.app
+            .post("/api/wallets/restore/shamir")
+            .add_header("X-API-KEY", API_KEY)
+            .json(&json!({ "name": name, "shares": shares, "password": "R3storedVault!" }))
+            .await
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_2_of_3_round_trip() {
+    let h = build().await;
+    let shares = h.shares(2, 3).await;
+    assert_eq!(shares.len(), 3);
+    for (i, share) in shares.iter().enumerate() {
+        assert_eq!(share["index"], i + 1);
+        assert_eq!(share["threshold"], 2);
+        assert_eq!(share["backup_id"], shares[0]["backup_id"]);
+        assert!(share["checksum"].as_str().is_some_and(|c| c.len() == 16));
+    }
+
+    let res = h.restore("vault_copy", &[shares[2].clone(), shares[0].clone()]).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["address"], h.address);
+    // 恢复的wallet已落库
+    assert!(h.storage.load_wallet("vault_copy").await.is_ok());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_3_of_5_round_trip() {
+    let h = build().await;
+    let shares = h.shares(3, 5).await;
+    assert_eq!(shares.len(), 5);
+
+    let res = h.restore("too_few", &shares[3..]).await;
+    res.assert_status_bad_request();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "INSUFFICIENT_SHARES");
+    assert!(body["error"]["message"].as_str().unwrap().contains("needs 3"));
+
+    let res = h.restore("vault_copy", &[shares[1].clone(), shares[4].clone(), shares[3].clone()]).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["address"], h.address);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_bad_shares_and_parameters() {
+    let h = build().await;
+    for (threshold, total) in [(1, 3), (2, 17), (4, 3)] {
+        let res = h.backup(threshold, total).await;
+        res.assert_status_bad_request();
+        assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_SHARE_PARAMETERS");
+    }
+
+    let shares = h.shares(2, 3).await;
+    let res = h.restore("dup", &[shares[1].clone(), shares[1].clone()]).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "DUPLICATE_SHARE");
+
+    let mut tampered = shares[1].clone();
+    tampered["index"] = json!(3);
+    let res = h.restore("tampered", &[shares[0].clone(), tampered]).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "SHARE_CHECKSUM_MISMATCH");
+
+    let other = h.shares(2, 3).await;
+    let res = h.restore("mixed", &[shares[0].clone(), other[1].clone()]).await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "SHARE_BACKUP_MISMATCH");
+
+    let res = h.restore("vault", &shares).await;
+    res.assert_status(axum::http::StatusCode::CONFLICT);
+
+    let res = h
+        .app
+        .post("/api/wallets/vault/backup/shamir")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "password": "Wr0ngSecret!", "threshold": 2, "total": 3 }))
+        .await;
+    res.assert_status_unauthorized();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_PASSWORD");
+}
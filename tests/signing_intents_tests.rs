@@ -0,0 +1,265 @@
+//! sign意图日志：在关键区的每个步骤之间模拟崩溃，validate对账结果
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionRequest, H256, U256};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::{BroadcastChain, IntentError, ReconcileReport, SigningIntentLog};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{
+    WalletStorage, INTENT_BROADCAST, INTENT_RECORDED, INTENT_RELEASED, INTENT_SIGNED,
+};
+
+const WALLET: &str = "hot";
+const NETWORK: &str = "eth";
+const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const API_KEY: &str = "signing-intents-admin-key";
+
+/// 节点：pending nonce = 已收到的transaction数；收到即可查到
+#[derive(Default)]
+struct MockChain {
+    received: Mutex<Vec<H256>>,
+}
+
+impl MockChain {
+    fn broadcasts(&self) -> usize {
+        self.received.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        tx.set_nonce(self.broadcasts() as u64);
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(1_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        let hash = H256::from(ethers::utils::keccak256(&raw));
+        self.received.lock().unwrap().push(hash);
+        Ok(hash)
+    }
+
+    async fn transaction_known(&self, _network: &str, tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(self.received.lock().unwrap().contains(&tx_hash))
+    }
+}
+
+struct Harness {
+    url: String,
+    chain: Arc<MockChain>,
+    signer: LocalWallet,
+    /// transactions 按 wallets.id 记录
+    wallet_id: String,
+    _dir: tempfile::TempDir,
+}
+
+impl Harness {
+    async fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display());
+        // 落库wallet写审计记录，其 MAC 需要 WALLET_ENC_KEY
+        std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+        let storage = WalletStorage::new_with_url(&url).await.unwrap();
+        storage.store_wallet(WALLET, b"blob", false).await.unwrap();
+        let wallet_id = storage.wallet_id(WALLET).await.unwrap().unwrap();
+        Self { url, chain: Arc::new(MockChain::default()), signer: KEY.parse().unwrap(), wallet_id, _dir: dir }
+    }
+
+    /// 每次调用都重新打开数据库文件，相当于进程重启
+    async fn open(&self) -> (Arc<WalletStorage>, SigningIntentLog) {
+        let storage = Arc::new(WalletStorage::new_with_url(&self.url).await.unwrap());
+        (storage.clone(), SigningIntentLog::new(storage, self.chain.clone()))
+    }
+
+    async fn prepared(&self) -> TypedTransaction {
+        let mut tx = self.transfer();
+        tx.set_from(self.signer.address());
+        self.chain.prepare(NETWORK, &mut tx).await.unwrap();
+        tx
+    }
+
+    fn transfer(&self) -> TypedTransaction {
+        TransactionRequest::new()
+            .to("0x000000000000000000000000000000000000dEaD".parse::<Address>().unwrap())
+            .value(U256::exp10(17))
+            .into()
+    }
+}
+
+async fn state_of(storage: &WalletStorage, id: &str) -> String {
+    storage.get_signing_intent(id).await.unwrap().unwrap().state
+}
+
+#[tokio::test]
+async fn test_crash_before_signing_releases_nonce() {
+    let h = Harness::new().await;
+    let (storage, log) = h.open().await;
+    let intent = log.begin(WALLET, NETWORK, &h.prepared().await).await.unwrap();
+    drop(log);
+
+    // 未决意图占住 nonce 0：再次发送被拒绝，且没有广播
+    let (_, log) = h.open().await;
+    let refused = log.send(WALLET, NETWORK, &h.signer, h.transfer()).await;
+    assert!(matches!(refused, Err(IntentError::NonceInUse { nonce: 0, .. })));
+    assert_eq!(h.chain.broadcasts(), 0);
+
+    let report = log.reconcile(0).await.unwrap();
+    assert_eq!(report, ReconcileReport { checked: 1, recorded: 0, released: 1, errors: 0 });
+    assert_eq!(state_of(&storage, &intent.id).await, INTENT_RELEASED);
+
+    // nonce 0 重新可用
+    let tx_hash = log.send(WALLET, NETWORK, &h.signer, h.transfer()).await.unwrap();
+    assert_eq!(h.chain.broadcasts(), 1);
+    let recorded = storage.get_wallet_transactions(&h.wallet_id).await.unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].tx_hash, tx_hash);
+}
+
+#[tokio::test]
+async fn test_crash_after_signing_without_broadcast_releases_nonce() {
+    let h = Harness::new().await;
+    let (storage, log) = h.open().await;
+    let tx = h.prepared().await;
+    let intent = log.begin(WALLET, NETWORK, &tx).await.unwrap();
+    log.sign(&intent, &h.signer, &tx).await.unwrap();
+    drop(log);
+
+    let (_, log) = h.open().await;
+    assert_eq!(state_of(&storage, &intent.id).await, INTENT_SIGNED);
+    let report = log.reconcile(0).await.unwrap();
+    assert_eq!(report.released, 1);
+    assert_eq!(state_of(&storage, &intent.id).await, INTENT_RELEASED);
+    // 对账从不补发
+    assert_eq!(h.chain.broadcasts(), 0);
+    assert!(storage.get_wallet_transactions(&h.wallet_id).await.unwrap().is_empty());
+
+    log.send(WALLET, NETWORK, &h.signer, h.transfer()).await.unwrap();
+    assert_eq!(h.chain.broadcasts(), 1);
+}
+
+#[tokio::test]
+async fn test_crash_after_rpc_returned_records_transaction() {
+    let h = Harness::new().await;
+    let (storage, log) = h.open().await;
+    let tx = h.prepared().await;
+    let intent = log.begin(WALLET, NETWORK, &tx).await.unwrap();
+    let raw = log.sign(&intent, &h.signer, &tx).await.unwrap();
+    // 节点已收到，但进程在写入 broadcast 状态前退出
+    h.chain.send_raw_transaction(NETWORK, raw).await.unwrap();
+    drop(log);
+
+    let (_, log) = h.open().await;
+    let report = log.reconcile(0).await.unwrap();
+    assert_eq!(report, ReconcileReport { checked: 1, recorded: 1, released: 0, errors: 0 });
+    let intent = storage.get_signing_intent(&intent.id).await.unwrap().unwrap();
+    assert_eq!(intent.state, INTENT_RECORDED);
+
+    let recorded = storage.get_wallet_transactions(&h.wallet_id).await.unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(Some(&recorded[0].tx_hash), intent.tx_hash.as_ref());
+    assert_eq!(recorded[0].amount, "0.100000000000000000");
+    assert_eq!(h.chain.broadcasts(), 1);
+
+    // 下一笔使用 nonce 1
+    log.send(WALLET, NETWORK, &h.signer, h.transfer()).await.unwrap();
+    assert_eq!(h.chain.broadcasts(), 2);
+}
+
+#[tokio::test]
+async fn test_crash_before_recording_completes_bookkeeping_once() {
+    let h = Harness::new().await;
+    let (storage, log) = h.open().await;
+    let tx = h.prepared().await;
+    let intent = log.begin(WALLET, NETWORK, &tx).await.unwrap();
+    let raw = log.sign(&intent, &h.signer, &tx).await.unwrap();
+    log.broadcast(&intent, raw).await.unwrap();
+    drop(log);
+
+    let (_, log) = h.open().await;
+    assert_eq!(state_of(&storage, &intent.id).await, INTENT_BROADCAST);
+    assert_eq!(log.reconcile(0).await.unwrap().recorded, 1);
+    assert_eq!(state_of(&storage, &intent.id).await, INTENT_RECORDED);
+
+    // 终态不再处理
+    assert_eq!(log.reconcile(0).await.unwrap(), ReconcileReport::default());
+    assert_eq!(storage.get_wallet_transactions(&h.wallet_id).await.unwrap().len(), 1);
+    assert_eq!(h.chain.broadcasts(), 1);
+
+    // recorded 的 nonce 不能复用
+    let replay = log.begin(WALLET, NETWORK, &tx).await;
+    assert!(matches!(replay, Err(IntentError::NonceInUse { nonce: 0, .. })));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_admin_reconcile_endpoint() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var(
+        "USERS_DATABASE_URL",
+        format!("sqlite://{}", dir.path().join("users.db").display()),
+    );
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let chain = Arc::new(MockChain::default());
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap()
+    .with_broadcast_chain(chain.clone());
+    server.storage.store_wallet(WALLET, b"blob", false).await.unwrap();
+
+    let signer: LocalWallet = KEY.parse().unwrap();
+    let mut tx: TypedTransaction = TransactionRequest::new()
+        .to("0x000000000000000000000000000000000000dEaD".parse::<Address>().unwrap())
+        .value(1u64)
+        .into();
+    tx.set_from(signer.address());
+    chain.prepare(NETWORK, &mut tx).await.unwrap();
+    let intent = server.signing_intents.begin(WALLET, NETWORK, &tx).await.unwrap();
+
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    app.post("/api/admin/intents/reconcile").json(&json!({})).await.assert_status_unauthorized();
+
+    // 默认宽限期内的意图视为仍在进行中
+    let res = app
+        .post("/api/admin/intents/reconcile")
+        .add_header("Authorization", API_KEY)
+        .json(&json!({}))
+        .await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["checked"], 0);
+
+    let res = app
+        .post("/api/admin/intents/reconcile")
+        .add_header("Authorization", API_KEY)
+        .json(&json!({ "min_age_secs": 0 }))
+        .await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>(), json!({ "checked": 1, "recorded": 0, "released": 1, "errors": 0 }));
+    assert_eq!(state_of(&storage, &intent.id).await, INTENT_RELEASED);
+}
@@ -0,0 +1,305 @@
+//! wallet支出上限：按最小单位整数比较，恰好达到当日上限的一笔放行、多 1 wei 即 403；
+//! 只计最近 24 小时内 pending 与 confirmed 的发送，收到的转账不计入；并发的发送合计不超过上限；
+//! 上限只能由 admin 设置
+
+mod util;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use chrono::{Duration, Utc};
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, H256, U256};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::storage::{
+    LedgerScope, NewLedgerEntry, TransactionRecord, WalletStorage, DIRECTION_IN, NATIVE_TOKEN, TX_ENTRY_INDEX,
+};
+
+const API_KEY: &str = "spending-limits-admin-key";
+const SESSION: &str = "spending-limits-session";
+const WALLET: &str = "allowance";
+const PASSWORD: &str = "Sp3nding!Limit#2024";
+const ALICE: &str = "0x000000000000000000000000000000000000dead";
+const BOB: &str = "0x000000000000000000000000000000000000beef";
+const ETH: u128 = 1_000_000_000_000_000_000;
+
+/// 节点：依次分配 nonce，记录广播过的transaction数
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    broadcast: Mutex<usize>,
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let nonce = match tx.nonce() {
+            Some(nonce) => nonce.as_u64(),
+            None => {
+                let mut next = self.nonce.lock().unwrap();
+                *next += 1;
+                *next - 1
+            }
+        };
+        tx.set_nonce(nonce);
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        *self.broadcast.lock().unwrap() += 1;
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_count(&self, _network: &str, _address: Address) -> Result<u64, WalletError> {
+        Ok(*self.nonce.lock().unwrap())
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    chain: Arc<MockChain>,
+    storage: Arc<WalletStorage>,
+    wallet_id: String,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = Arc::new(MockChain::default());
+    let server =
+        util::test_server(dir.path(), util::memory_config(), Some(API_KEY)).await.with_broadcast_chain(chain.clone());
+
+    let user_id = util::sign_in(&server, "allowance@example.com", SESSION).await;
+    let wallet_id = util::create_wallet(&server, WALLET, PASSWORD).await;
+    let address = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+    server.user_db.link_wallet(&user_id, WALLET, &format!("{:#x}", address), None).await.unwrap();
+
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, chain, storage, wallet_id, _dir: dir }
+}
+
+impl Harness {
+    async fn send(&self, to: &str, amount: &str) -> axum_test::TestResponse {
+        self.app
+            .post(&format!("/api/wallets/{}/send", WALLET))
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .json(&json!({ "to": to, "amount": amount, "network": "eth", "password": PASSWORD }))
+            .await
+    }
+
+    async fn put_limits(&self, body: Value) -> axum_test::TestResponse {
+        self.app.put(&format!("/api/wallets/{}/limits", WALLET)).add_header("X-API-KEY", API_KEY).json(&body).await
+    }
+
+    fn broadcast(&self) -> usize {
+        *self.chain.broadcast.lock().unwrap()
+    }
+
+    fn seeded(&self, id: &str, amount: &str, status: &str, created_at: chrono::DateTime<Utc>) -> TransactionRecord {
+        TransactionRecord {
+            id: id.to_string(),
+            wallet_id: self.wallet_id.clone(),
+            tx_hash: format!("0x{:0>64}", id),
+            network: "eth".to_string(),
+            from_address: BOB.to_string(),
+            to_address: ALICE.to_string(),
+            amount: amount.to_string(),
+            fee: "0".to_string(),
+            status: status.to_string(),
+            created_at,
+            confirmed_at: None,
+            integrity_hash: String::new(),
+            fee_actual: None,
+            block_number: None,
+        }
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_send_that_exactly_hits_the_daily_cap_is_allowed() {
+    let h = build().await;
+    h.put_limits(json!({ "network": "eth", "max_per_day": "0.3" })).await.assert_status_ok();
+
+    h.send(ALICE, "0.1").await.assert_status_ok();
+    // 0.1 + 0.2 恰好等于上限
+    h.send(BOB, "0.2").await.assert_status_ok();
+    assert_eq!(h.broadcast(), 2);
+
+    // 再多 1 wei 即超出
+    let res = h.send(ALICE, "0.000000000000000001").await;
+    res.assert_status_forbidden();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "DAILY_SPENDING_LIMIT_EXCEEDED");
+    assert_eq!(h.broadcast(), 2);
+
+    let res = h.app.get(&format!("/api/wallets/{}/limits", WALLET)).add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["limits"][0]["network"], "eth");
+    assert_eq!(body["limits"][0]["max_per_day"], "0.3");
+    assert_eq!(body["limits"][0]["spent_last_day"], "0.3");
+    assert!(body["limits"][0]["max_per_tx"].is_null());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_per_transaction_limit() {
+    let h = build().await;
+    h.put_limits(json!({ "network": "eth", "max_per_tx": "0.25", "max_per_day": "1" })).await.assert_status_ok();
+
+    let res = h.send(ALICE, "0.250000000000000001").await;
+    res.assert_status_forbidden();
+    assert_eq!(res.json::<Value>()["error"]["code"], "SPENDING_LIMIT_EXCEEDED");
+    h.send(ALICE, "0.25").await.assert_status_ok();
+    assert_eq!(h.broadcast(), 1);
+
+    // 其他network不受 eth 上限约束
+    assert!(h.storage.spending_limit(WALLET, "polygon").await.unwrap().is_none());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_only_recent_pending_and_confirmed_sends_count() {
+    let h = build().await;
+    let now = Utc::now();
+    h.storage.store_transaction(&h.seeded("1", "1.5", "confirmed", now - Duration::hours(1))).await.unwrap();
+    h.storage.store_transaction(&h.seeded("2", "0.5", "pending", now - Duration::minutes(5))).await.unwrap();
+    h.storage.store_transaction(&h.seeded("3", "4", "failed", now - Duration::hours(2))).await.unwrap();
+    h.storage.store_transaction(&h.seeded("4", "7", "confirmed", now - Duration::hours(25))).await.unwrap();
+    assert_eq!(h.storage.sent_in_last_day(WALLET, "eth").await.unwrap(), 2 * ETH);
+
+    // 已发送 2 ETH，上限 3 ETH：1 ETH 恰好放行，多 1 wei 拒绝
+    let limit = h.storage.set_spending_limit(WALLET, "eth", None, Some(3 * ETH), "admin").await.unwrap().unwrap();
+    assert_eq!(limit.check(ETH, 2 * ETH), Ok(()));
+    assert!(limit.check(ETH + 1, 2 * ETH).is_err());
+
+    let res = h.send(ALICE, "1.000000000000000001").await;
+    res.assert_status_forbidden();
+    assert_eq!(res.json::<Value>()["error"]["code"], "DAILY_SPENDING_LIMIT_EXCEEDED");
+    h.send(ALICE, "1").await.assert_status_ok();
+    assert_eq!(h.storage.sent_in_last_day(WALLET, "eth").await.unwrap(), 3 * ETH);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_incoming_transfers_do_not_consume_the_daily_cap() {
+    let h = build().await;
+    // 回填写入的收款：transactions 中为 confirmed，ledger 中方向为 in
+    let scope = LedgerScope { wallet_name: WALLET, network: "eth", address: ALICE, source: "rpc" };
+    let incoming = NewLedgerEntry {
+        tx_hash: format!("0x{:0>64}", "1"),
+        log_index: TX_ENTRY_INDEX,
+        block_number: 100,
+        direction: DIRECTION_IN,
+        token: NATIVE_TOKEN.to_string(),
+        counterparty: Some(BOB.to_string()),
+        amount: U256::from(5 * ETH),
+        fee: U256::zero(),
+        success: true,
+        occurred_at: Utc::now().timestamp() - 60,
+    };
+    assert_eq!(h.storage.record_onchain_transfers(&scope, &[incoming]).await.unwrap(), 1);
+    h.storage.store_transaction(&h.seeded("2", "0.5", "confirmed", Utc::now() - Duration::minutes(5))).await.unwrap();
+    assert_eq!(h.storage.sent_in_last_day(WALLET, "eth").await.unwrap(), ETH / 2);
+
+    h.put_limits(json!({ "network": "eth", "max_per_day": "1" })).await.assert_status_ok();
+    h.send(BOB, "0.5").await.assert_status_ok();
+    assert_eq!(h.broadcast(), 1);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_concurrent_sends_do_not_exceed_the_daily_cap_together() {
+    let h = build().await;
+    h.put_limits(json!({ "network": "eth", "max_per_day": "0.3" })).await.assert_status_ok();
+
+    // 两笔各自都在上限内，合计超出：只能有一笔通过
+    let (first, second) = tokio::join!(h.send(ALICE, "0.2"), h.send(BOB, "0.2"));
+    let mut statuses = [first.status_code().as_u16(), second.status_code().as_u16()];
+    statuses.sort();
+    assert_eq!(statuses, [200, 403]);
+    assert_eq!(h.broadcast(), 1);
+    assert_eq!(h.storage.sent_in_last_day(WALLET, "eth").await.unwrap(), ETH / 5);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_batch_items_count_toward_the_daily_cap() {
+    let h = build().await;
+    h.put_limits(json!({ "network": "eth", "max_per_day": "0.3" })).await.assert_status_ok();
+    let transfer =
+        |to: &str, amount: &str| json!({ "to": to, "amount": amount, "network": "eth", "password": PASSWORD });
+
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/send_batch", WALLET))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "transactions": [transfer(ALICE, "0.1"), transfer(BOB, "0.2"), transfer(ALICE, "0.01")] }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["results"][0]["status"], "sent");
+    assert_eq!(body["results"][1]["status"], "sent");
+    assert_eq!(body["results"][2]["status"], "failed");
+    assert_eq!(body["results"][2]["code"], "DAILY_SPENDING_LIMIT_EXCEEDED");
+    assert_eq!(h.broadcast(), 2);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_limits_are_set_by_admin_only() {
+    let h = build().await;
+    let url = format!("/api/wallets/{}/limits", WALLET);
+
+    // owner 可以查看，但不能放宽
+    let res = h.app.get(&url).add_header("Authorization", format!("Bearer {}", SESSION)).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["limits"], json!([]));
+    let res = h
+        .app
+        .put(&url)
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "network": "eth", "max_per_day": "100" }))
+        .await;
+    res.assert_status_unauthorized();
+    assert_eq!(res.json::<Value>()["error"]["code"], "AUTH_FAILED");
+
+    for (body, code) in [
+        (json!({ "network": "eth", "max_per_tx": "2", "max_per_day": "1" }), "INVALID_SPENDING_LIMIT"),
+        (json!({ "network": "eth", "max_per_day": "-1" }), "INVALID_AMOUNT"),
+        (json!({ "network": "btc", "max_per_day": "1" }), "UNSUPPORTED_NETWORK"),
+        (json!({ "max_per_day": "1" }), "INVALID_NETWORK"),
+    ] {
+        let res = h.put_limits(body.clone()).await;
+        res.assert_status_bad_request();
+        assert_eq!(res.json::<Value>()["error"]["code"], code, "{}", body);
+    }
+
+    let res = h.put_limits(json!({ "network": "eth", "max_per_tx": "0.5", "max_per_day": "2" })).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["limits"][0]["max_per_tx"], "0.5");
+    assert_eq!(body["limits"][0]["updated_by"], "admin");
+    let stored = h.storage.spending_limit(WALLET, "eth").await.unwrap().unwrap();
+    assert_eq!(stored.max_per_tx, Some(ETH / 2));
+    assert_eq!(stored.max_per_day, Some(2 * ETH));
+
+    // 两项都为 null 时删除
+    let res = h.put_limits(json!({ "network": "eth", "max_per_tx": null, "max_per_day": null })).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["limits"], json!([]));
+    assert!(h.storage.spending_limit(WALLET, "eth").await.unwrap().is_none());
+}
@@ -32,6 +32,10 @@ fn test_blockchain_config_custom() {
         name: "Ethereum Mainnet".to_string(),
         rpc_url: "https://mainnet.infura.io".to_string(),
         chain_id: 1u64,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     let config = BlockchainConfig {
@@ -47,6 +51,10 @@ fn test_network_config_ethereum() {
         name: "Ethereum Mainnet".to_string(),
         rpc_url: "https://mainnet.infura.io".to_string(),
         chain_id: 1u64,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     };
     
     assert_eq!(eth_config.chain_id, 1u64);
@@ -58,6 +66,10 @@ fn test_network_config_polygon() {
         name: "Polygon Mainnet".to_string(),
         rpc_url: "https://polygon-rpc.com".to_string(),
         chain_id: 137u64,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     };
     
     assert_eq!(polygon_config.chain_id, 137u64);
@@ -69,6 +81,10 @@ fn test_network_config_bsc() {
         name: "BSC Mainnet".to_string(),
         rpc_url: "https://bsc-dataseed.binance.org".to_string(),
         chain_id: 56u64,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     };
     
     assert_eq!(bsc_config.chain_id, 56u64);
@@ -82,18 +98,30 @@ fn test_multiple_network_configs() {
         name: "Ethereum Mainnet".to_string(),
         rpc_url: "https://mainnet.infura.io".to_string(),
         chain_id: 1u64,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     networks.insert("polygon".to_string(), NetworkConfig {
         name: "Polygon Mainnet".to_string(),
         rpc_url: "https://polygon-rpc.com".to_string(),
         chain_id: 137u64,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     networks.insert("bsc".to_string(), NetworkConfig {
         name: "BSC Mainnet".to_string(),
         rpc_url: "https://bsc-dataseed.binance.org".to_string(),
         chain_id: 56u64,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     let config = BlockchainConfig {
@@ -174,6 +202,10 @@ fn test_concurrent_config_creation() {
                 name: format!("Network {}", i),
                 rpc_url: format!("https://rpc-{}.example.com", i),
                 chain_id: i as u64,
+                explorer_url_template: None,
+                rate_limit: None,
+                fallback_endpoints: Vec::new(),
+                pending_expiry_seconds: None,
             });
             
             BlockchainConfig {
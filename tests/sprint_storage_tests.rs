@@ -66,6 +66,8 @@ async fn test_store_transaction() {
         created_at: Utc::now(),
         confirmed_at: None,
         integrity_hash: String::new(), // 会被自动计算
+        fee_actual: None,
+        block_number: None,
     };
     
     let result = storage.store_transaction(&tx_record).await;
@@ -123,6 +125,10 @@ async fn test_store_bridge_transaction() {
         updated_at: Utc::now(),
         fee_amount: Some("1.0".to_string()),
         estimated_completion_time: None,
+        amount_minimal_source: None,
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
     };
     
     let result = storage.store_bridge_transaction(&bridge_tx).await;
@@ -150,6 +156,10 @@ async fn test_get_bridge_transaction() {
         updated_at: Utc::now(),
         fee_amount: Some("1.0".to_string()),
         estimated_completion_time: None,
+        amount_minimal_source: None,
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
     };
     
     storage.store_bridge_transaction(&bridge_tx).await.unwrap();
@@ -182,6 +192,10 @@ async fn test_update_bridge_transaction_status() {
         updated_at: Utc::now(),
         fee_amount: Some("1.0".to_string()),
         estimated_completion_time: None,
+        amount_minimal_source: None,
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
     };
     
     storage.store_bridge_transaction(&bridge_tx).await.unwrap();
@@ -295,6 +309,8 @@ async fn test_concurrent_transaction_storage() {
                 created_at: Utc::now(),
                 confirmed_at: None,
                 integrity_hash: String::new(),
+                fee_actual: None,
+                block_number: None,
             };
             storage_clone.store_transaction(&tx_record).await
         });
@@ -372,6 +388,8 @@ async fn test_transaction_integrity_hash() {
         created_at: Utc::now(),
         confirmed_at: None,
         integrity_hash: String::new(),
+        fee_actual: None,
+        block_number: None,
     };
     
     // 验证API可以调用（不强制要求成功，因为可能有外键约束）
@@ -476,6 +494,8 @@ async fn test_store_transaction_with_empty_fields() {
         created_at: Utc::now(),
         confirmed_at: None,
         integrity_hash: String::new(),
+        fee_actual: None,
+        block_number: None,
     };
     
     // 存储应该成功（数据库层面允许）
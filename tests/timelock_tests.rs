@@ -0,0 +1,308 @@
+//! 时间锁transaction：解锁前拒绝释放、托管方份额、保留 nonce 段与普通发送的隔离、
+//! 释放时的 gas 下限与 nonce check，以及过期
+
+mod util;
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use ethers::providers::{MockProvider, Provider};
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, H256, U256};
+use ethers::utils::rlp::Rlp;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::blockchain::gas_oracle::ProviderGasOracle;
+use defi_hot_wallet::core::clock::FixedClock;
+use defi_hot_wallet::core::config::{TimelockConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::core::ids::SequentialIds;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::storage::{WalletStorage, FUTURE_LANE};
+
+const API_KEY: &str = "timelock-admin-key";
+const OWNER_TOKEN: &str = "timelock-owner-token";
+const WALLET: &str = "vesting";
+const PASSWORD: &str = "V3sting!Vault#2024";
+const ALICE: &str = "0x000000000000000000000000000000000000a11c";
+const BOB: &str = "0x000000000000000000000000000000000000b0b0";
+const NOW: i64 = 1_700_000_000;
+const DAY: i64 = 86_400;
+const GWEI: u64 = 1_000_000_000;
+
+/// 节点：pending nonce 只在广播时前进，`prepare` 的 gas price 为 3 gwei
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    block: Mutex<u64>,
+    sent: Mutex<Vec<TypedTransaction>>,
+}
+
+impl MockChain {
+    fn sent(&self) -> Vec<TypedTransaction> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        tx.set_nonce(*self.nonce.lock().unwrap());
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3 * GWEI);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        let (tx, _) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        let mut nonce = self.nonce.lock().unwrap();
+        assert_eq!(tx.nonce().unwrap().as_u64(), *nonce, "node only accepts the next nonce");
+        *nonce += 1;
+        self.sent.lock().unwrap().push(tx);
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_count(&self, _network: &str, _address: Address) -> Result<u64, WalletError> {
+        Ok(*self.nonce.lock().unwrap())
+    }
+
+    async fn block_number(&self, _network: &str) -> Result<u64, WalletError> {
+        Ok(*self.block.lock().unwrap())
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    chain: Arc<MockChain>,
+    clock: Arc<FixedClock>,
+    storage: Arc<WalletStorage>,
+    /// eth_gasPrice 的应答，后进先出
+    node: MockProvider,
+    _dir: tempfile::TempDir,
+}
+
+/// 时间锁 nonce 从账户 nonce + 1 开始，每段 2 个
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let config = WalletConfig {
+        timelocks: TimelockConfig { lane_offset: 1, lane_size: 2, ..Default::default() },
+        ..util::file_config(dir.path())
+    };
+    let clock = Arc::new(FixedClock::at(NOW));
+    let chain = Arc::new(MockChain::default());
+    let (provider, node) = Provider::mocked();
+    let server = util::test_server_with_sources(
+        dir.path(),
+        config,
+        Some(API_KEY),
+        clock.clone(),
+        Arc::new(SequentialIds::new()),
+    )
+    .await
+    .with_broadcast_chain(chain.clone())
+    .with_gas_oracle(Arc::new(ProviderGasOracle::<MockProvider>::new().with_provider("eth", provider)));
+
+    let user_id = util::sign_in(&server, "timelock-owner@example.com", OWNER_TOKEN).await;
+    util::create_wallet(&server, WALLET, PASSWORD).await;
+    let address = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+    server.user_db.link_wallet(&user_id, WALLET, &format!("{:#x}", address), None).await.unwrap();
+
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, chain, clock, storage, node, _dir: dir }
+}
+
+impl Harness {
+    async fn lock(&self, body: Value) -> axum_test::TestResponse {
+        let mut body = body;
+        body["network"] = json!("eth");
+        body["password"] = json!(PASSWORD);
+        self.app
+            .post(&format!("/api/wallets/{}/timelocks", WALLET))
+            .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+            .json(&body)
+            .await
+    }
+
+    /// 一天后解锁、向 ALICE 转 1 ETH 的时间锁
+    async fn lock_for_a_day(&self) -> Value {
+        let res = self.lock(json!({ "to": ALICE, "value": "1000000000000000000", "unlock_at": NOW + DAY })).await;
+        res.assert_status(StatusCode::CREATED);
+        res.json()
+    }
+
+    async fn release(&self, id: &str, share: &str) -> axum_test::TestResponse {
+        self.app
+            .post(&format!("/api/timelocks/{}/release", id))
+            .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+            .json(&json!({ "custodian_share": share }))
+            .await
+    }
+
+    async fn send(&self, to: &str, amount: &str) -> axum_test::TestResponse {
+        self.app
+            .post(&format!("/api/wallets/{}/send", WALLET))
+            .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+            .json(&json!({ "to": to, "amount": amount, "network": "eth", "password": PASSWORD }))
+            .await
+    }
+
+    fn gas_price(&self, gwei: u64) {
+        self.node.push(U256::from(gwei * GWEI)).unwrap();
+    }
+}
+
+fn code(res: &axum_test::TestResponse) -> String {
+    res.json::<Value>()["error"]["code"].as_str().unwrap_or_default().to_string()
+}
+
+fn id_and_share(created: &Value) -> (String, String) {
+    let id = created["timelock"]["id"].as_str().unwrap().to_string();
+    (id, created["custodian_share"].as_str().unwrap().to_string())
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_lock_is_released_only_after_unlock_with_the_custodian_share() {
+    let h = build().await;
+    let created = h.lock_for_a_day().await;
+    let (id, share) = id_and_share(&created);
+    assert_eq!(created["timelock"]["status"], "locked");
+    assert_eq!(created["timelock"]["nonce"], 1);
+    assert_eq!(created["timelock"]["expires_at"], NOW + 8 * DAY);
+    // 密封内容不出现在响应里
+    assert!(created["timelock"].get("sealed_tx").is_none());
+    assert!(created["timelock"].get("server_share").is_none());
+
+    let res = h.release(&id, &share).await;
+    res.assert_status(StatusCode::CONFLICT);
+    assert_eq!(code(&res), "TIMELOCK_NOT_YET_UNLOCKED");
+
+    // 先用掉 nonce 0，时间锁的 nonce 才轮到
+    h.send(BOB, "0.1").await.assert_status_ok();
+    h.clock.advance(chrono::Duration::seconds(DAY));
+    let mut forged = hex::decode(&share).unwrap();
+    forged[4] ^= 1;
+    let res = h.release(&id, &hex::encode(forged)).await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(code(&res), "INVALID_CUSTODIAN_SHARE");
+    assert_eq!(h.chain.sent().len(), 1);
+
+    h.gas_price(2);
+    let res = h.release(&id, &share).await;
+    res.assert_status_ok();
+    let released: Value = res.json();
+    assert_eq!(released["status"], "released");
+    assert!(released["tx_hash"].as_str().unwrap().starts_with("0x"));
+    let sent = h.chain.sent();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[1].to_addr().unwrap(), &ALICE.parse::<Address>().unwrap());
+    assert_eq!(sent[1].nonce().unwrap().as_u64(), 1);
+
+    // 最后一个时间锁释放后 nonce 段归还给普通发送
+    let from = format!("{:#x}", sent[1].from().unwrap());
+    assert!(h.storage.nonce_lane("eth", &from, FUTURE_LANE).await.unwrap().is_none());
+    h.send(BOB, "0.2").await.assert_status_ok();
+
+    let res = h.release(&id, &share).await;
+    res.assert_status(StatusCode::CONFLICT);
+    assert_eq!(code(&res), "TIMELOCK_NOT_LOCKED");
+    let audits = h.storage.get_audit_logs(Some(WALLET)).await.unwrap();
+    assert!(audits.iter().any(|a| a.action == "timelock.created"));
+    assert!(audits.iter().any(|a| a.action == "timelock.released"));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_ordinary_sends_cannot_take_reserved_lane_nonces() {
+    let h = build().await;
+    let (first, first_share) = id_and_share(&h.lock_for_a_day().await);
+    let (second, second_share) = id_and_share(&h.lock_for_a_day().await);
+
+    // 段 [1, 3) 已满
+    let res = h.lock(json!({ "to": ALICE, "value": "1", "unlock_at": NOW + DAY })).await;
+    res.assert_status(StatusCode::CONFLICT);
+    assert_eq!(code(&res), "NONCE_LANE_EXHAUSTED");
+
+    h.send(BOB, "0.1").await.assert_status_ok();
+    let res = h.send(BOB, "0.2").await;
+    res.assert_status(StatusCode::CONFLICT);
+    assert_eq!(res.json::<Value>()["error"]["code"], "NONCE_LANE_RESERVED");
+    assert_eq!(h.chain.sent().len(), 1);
+
+    // 第二个时间锁（nonce 2）要等第一个先上链
+    h.clock.advance(chrono::Duration::seconds(DAY));
+    h.gas_price(3);
+    let res = h.release(&second, &second_share).await;
+    res.assert_status(StatusCode::CONFLICT);
+    assert_eq!(code(&res), "TIMELOCK_NONCE_GAP");
+    h.gas_price(3);
+    h.release(&first, &first_share).await.assert_status_ok();
+    assert_eq!(h.chain.sent().len(), 2);
+
+    let res = h.app.get(&format!("/api/wallets/{}/timelocks", WALLET)).add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    let statuses: Vec<Value> =
+        res.json::<Value>()["timelocks"].as_array().unwrap().iter().map(|t| t["status"].clone()).collect();
+    assert_eq!(statuses.iter().filter(|s| *s == "released").count(), 1);
+    assert_eq!(statuses.iter().filter(|s| *s == "locked").count(), 1);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_release_checks_gas_floor_and_account_nonce() {
+    let h = build().await;
+    let (id, share) = id_and_share(&h.lock_for_a_day().await);
+    h.clock.advance(chrono::Duration::seconds(DAY));
+
+    // sign时 3 gwei，现在 40 gwei：保持锁定，之后可以重试
+    h.gas_price(40);
+    let res = h.release(&id, &share).await;
+    res.assert_status(StatusCode::CONFLICT);
+    assert_eq!(code(&res), "TIMELOCK_GAS_BELOW_FLOOR");
+
+    // 账户 nonce 还是 0
+    h.gas_price(3);
+    let res = h.release(&id, &share).await;
+    res.assert_status(StatusCode::CONFLICT);
+    assert_eq!(code(&res), "TIMELOCK_NONCE_GAP");
+    assert!(h.chain.sent().is_empty());
+
+    // nonce 1 被外部transaction用掉：作废
+    *h.chain.nonce.lock().unwrap() = 2;
+    h.gas_price(3);
+    let res = h.release(&id, &share).await;
+    res.assert_status(StatusCode::CONFLICT);
+    assert_eq!(code(&res), "TIMELOCK_NONCE_CONSUMED");
+    let res = h.app.get(&format!("/api/wallets/{}/timelocks", WALLET)).add_header("X-API-KEY", API_KEY).await;
+    assert_eq!(res.json::<Value>()["timelocks"][0]["status"], "invalidated");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_unreleased_lock_expires_and_frees_its_lane() {
+    let h = build().await;
+    let res = h.lock(json!({ "to": ALICE, "value": "1", "unlock_at": NOW + 60, "expires_at": NOW + 120 })).await;
+    res.assert_status(StatusCode::CREATED);
+    let (id, share) = id_and_share(&res.json());
+    let res = h.lock(json!({ "to": ALICE, "value": "1", "unlock_at": NOW - 60 })).await;
+    res.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(code(&res), "INVALID_TIMELOCK");
+
+    h.send(BOB, "0.1").await.assert_status_ok();
+    h.clock.advance(chrono::Duration::seconds(120));
+    let res = h.release(&id, &share).await;
+    res.assert_status(StatusCode::GONE);
+    assert_eq!(code(&res), "TIMELOCK_EXPIRED");
+
+    // nonce 1 重新可用
+    h.send(BOB, "0.2").await.assert_status_ok();
+    assert_eq!(h.chain.sent().len(), 2);
+}
@@ -33,6 +33,7 @@ mod advanced_transaction_tests {
                 database_url: "sqlite::memory:".to_string(),
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             ..Default::default()
         };
@@ -51,7 +52,7 @@ mod advanced_transaction_tests {
     
     async fn create_test_wallet(server: &WalletServer, name: &str) {
         server.wallet_manager
-            .create_wallet(name, false)
+            .create_wallet(name, "test_password", false)
             .await
             .ok();
     }
@@ -297,8 +298,9 @@ mod advanced_transaction_tests {
                 to: format!("0x742d35Cc6634C0532925a3b844Bc9e7595f0bE{:x}", i),
                 amount: format!("0.{}", i + 1),
                 network: "eth".to_string(),
-                password: "test_password".to_string(),
+                password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
             };
             
             let request = Request::builder()
@@ -329,8 +331,9 @@ mod advanced_transaction_tests {
                 to: format!("0x742d35Cc6634C0532925a3b844Bc9e7595f0bE{:x}", i),
                 amount: "0.1".to_string(),
                 network: "eth".to_string(),
-                password: "test_password".to_string(),
+                password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
             };
             
             let request = Request::builder()
@@ -373,8 +376,9 @@ mod advanced_transaction_tests {
                 to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
                 amount: "0.1".to_string(),
                 network: network.to_string(),
-                password: "test_password".to_string(),
+                password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
             };
             
             let request = Request::builder()
@@ -408,8 +412,9 @@ mod advanced_transaction_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "0.000000000000000001".to_string(), // 1 wei
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -435,8 +440,9 @@ mod advanced_transaction_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "1.5e-8".to_string(), // 科学计数法
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -465,8 +471,9 @@ mod advanced_transaction_tests {
             to: "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string(),
             amount: "0.1".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -491,8 +498,9 @@ mod advanced_transaction_tests {
             to: "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".to_string(),
             amount: "0.1".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request = Request::builder()
@@ -520,8 +528,9 @@ mod advanced_transaction_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "invalid".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request1 = Request::builder()
@@ -541,8 +550,9 @@ mod advanced_transaction_tests {
             to: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
             amount: "0.1".to_string(),
             network: "eth".to_string(),
-            password: "test_password".to_string(),
+            password: Some("test_password".to_string()),
             client_request_id: None,
+            ..Default::default()
         };
         
         let request2 = Request::builder()
@@ -0,0 +1,224 @@
+//! 决策快照：服务端sign的transaction与当时通过的限额、策略、规则哈希一起入库，
+//! 经 `/api/transactions/:hash/status`（admin）与事件日志导出，且不含任何凭据
+
+mod util;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Bytes, TransactionReceipt, H256};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use defi_hot_wallet::anomaly_detection::{AnomalyDetectionConfig, AnomalyDetector, DetectionMode};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::BroadcastChain;
+use defi_hot_wallet::operations::BundleService;
+use defi_hot_wallet::security::redaction::contains_secret;
+use defi_hot_wallet::storage::journal_events::TRANSACTION_DECIDED;
+use defi_hot_wallet::storage::WalletStorage;
+
+const API_KEY: &str = "transaction-decision-admin-key";
+const SESSION: &str = "transaction-decision-session";
+const WALLET: &str = "audited";
+const PASSWORD: &str = "Aud1ted!Vault#2024";
+const TO: &str = "0x000000000000000000000000000000000000dead";
+
+/// 节点：nonce 递增，gas 固定
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(3_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        Ok(H256::from(ethers::utils::keccak256(&raw)))
+    }
+
+    async fn transaction_known(&self, _network: &str, _tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(true)
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        _tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        Ok(None)
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    storage: Arc<WalletStorage>,
+    bundles: Arc<BundleService>,
+    user_id: String,
+    wallet_id: String,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let mut detector = AnomalyDetector::new();
+    detector.set_mode(DetectionMode::WarnOnly);
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY))
+        .await
+        .with_broadcast_chain(Arc::new(MockChain::default()))
+        .with_bundle_detector(detector);
+
+    let user_id = util::sign_in(&server, "auditee@example.com", SESSION).await;
+    let wallet_id = util::create_wallet(&server, WALLET, PASSWORD).await;
+    let address = server.wallet_manager.ethereum_signer(WALLET, PASSWORD).await.unwrap().address();
+    server.user_db.link_wallet(&user_id, WALLET, &format!("{:#x}", address), None).await.unwrap();
+
+    let storage = server.storage.clone();
+    let bundles = server.bundles.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, storage, bundles, user_id, wallet_id, _dir: dir }
+}
+
+impl Harness {
+    async fn send(&self, bearer: &str, amount: &str) -> String {
+        let res = self
+            .app
+            .post(&format!("/api/wallets/{}/send", WALLET))
+            .add_header("Authorization", format!("Bearer {}", bearer))
+            .json(&json!({ "to": TO, "amount": amount, "network": "eth", "password": PASSWORD }))
+            .await;
+        res.assert_status_ok();
+        res.json::<Value>()["tx_hash"].as_str().unwrap().to_string()
+    }
+
+    /// admin 状态接口返回的决策快照
+    async fn decision_of(&self, tx_hash: &str) -> Value {
+        let res = self
+            .app
+            .get(&format!("/api/transactions/{}/status", tx_hash))
+            .add_header("Authorization", API_KEY)
+            .await;
+        res.assert_status_ok();
+        res.json::<Value>()["decision"].clone()
+    }
+
+    async fn decided_events(&self) -> Vec<Value> {
+        let events = self.storage.journal_events(0, 1000).await.unwrap();
+        events.into_iter().filter(|e| e.event_type == TRANSACTION_DECIDED).map(|e| e.payload).collect()
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_server_signed_send_records_its_decision() {
+    let h = build().await;
+    h.storage.set_review_threshold(WALLET, Some("1"), "admin").await.unwrap();
+
+    let tx_hash = h.send(SESSION, "0.5").await;
+    let decision = h.decision_of(&tx_hash).await;
+    assert_eq!(decision["requested_by"], h.user_id.as_str());
+    assert_eq!(decision["review"], json!({ "threshold": "1", "required": false }));
+    assert_eq!(decision["network"], json!({ "network": "eth", "allowed": null }));
+    assert_eq!(decision["duplicate"]["allow_duplicate"], false);
+    assert!(decision["duplicate"]["window_secs"].is_u64());
+    assert_eq!(decision["fee"], json!({ "gas_limit": "21000", "gas_price": "3000000000" }));
+    // 会话请求没有 token 上限
+    assert!(decision.get("token").is_none());
+
+    // 之后放宽阈值不改变已记录的快照
+    h.storage.set_review_threshold(WALLET, Some("100"), "admin").await.unwrap();
+    assert_eq!(h.decision_of(&tx_hash).await, decision);
+
+    // 非 admin 看不到快照
+    h.app
+        .get(&format!("/api/transactions/{}/status", tx_hash))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await
+        .assert_status_unauthorized();
+
+    // 事件日志导出的是同一份快照
+    let events = h.decided_events().await;
+    let exported = events.iter().find(|p| p["tx_hash"] == tx_hash.as_str()).unwrap();
+    assert_eq!(exported["wallet_id"], h.wallet_id.as_str());
+    assert_eq!(exported["decision"], decision);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_wallet_token_send_records_the_cap() {
+    let h = build().await;
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/tokens", WALLET))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "capabilities": ["send"], "amount_cap": "0.5" }))
+        .await;
+    res.assert_status_ok();
+    let issued: Value = res.json();
+    let token = issued["token"].as_str().unwrap();
+
+    let tx_hash = h.send(token, "0.25").await;
+    let decision = h.decision_of(&tx_hash).await;
+    assert_eq!(decision["token"], json!({ "token_id": issued["id"], "amount_cap": "0.5" }));
+
+    // 快照里没有任何凭据
+    let record = h.storage.transaction_by_hash(&tx_hash).await.unwrap().unwrap();
+    let stored = h.storage.transaction_decision(&record.id).await.unwrap().unwrap().to_string();
+    for secret in [PASSWORD, API_KEY, SESSION, token] {
+        assert!(!stored.contains(secret), "{} leaked into {}", secret, stored);
+    }
+    assert!(!contains_secret(&stored), "{}", stored);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_bundle_decisions_carry_the_rules_hash() {
+    let h = build().await;
+    let submit = |amount: &'static str| {
+        h.app
+            .post(&format!("/api/wallets/{}/bundles", WALLET))
+            .add_header("Authorization", format!("Bearer {}", SESSION))
+            .json(&json!({
+                "password": PASSWORD,
+                "steps": [{ "id": "pay", "kind": "transfer", "network": "eth", "to": TO, "amount": amount }],
+            }))
+    };
+
+    let res = submit("0.1").await;
+    res.assert_status_ok();
+    let first: Value = res.json();
+    let before = h.bundles.rules_hash().await;
+    assert_eq!(before.len(), 32);
+
+    // 热更新规则后，新的发送记录新的哈希；旧快照不变
+    let mut config = AnomalyDetectionConfig::default();
+    config.rule_engine.high_value_threshold *= 2.0;
+    h.bundles.update_rules(config).await.unwrap();
+    let after = h.bundles.rules_hash().await;
+    assert_ne!(before, after);
+
+    let res = submit("0.2").await;
+    res.assert_status_ok();
+    let second: Value = res.json();
+
+    let events = h.decided_events().await;
+    let decision_of = |bundle: &Value| {
+        let id = bundle["id"].as_str().unwrap();
+        events.iter().find(|p| p["decision"]["bundle_id"] == id).unwrap()["decision"].clone()
+    };
+    let first = decision_of(&first);
+    assert_eq!(first["anomaly"]["rules_hash"], before.as_str());
+    assert_eq!(first["feature_flags"], json!({ "anomaly_blocking": true }));
+    assert_eq!(first["requested_by"], h.user_id.as_str());
+    assert_eq!(decision_of(&second)["anomaly"]["rules_hash"], after.as_str());
+}
@@ -0,0 +1,209 @@
+//! `GET /api/networks/:network/transactions/:hash` 集成测试（MockProvider）
+
+mod util;
+
+use axum_test::TestServer;
+use ethers::providers::{MockProvider, Provider};
+use ethers::types::{Address, Bytes, Log, Transaction, TransactionReceipt, H256, U256, U64};
+use ethers::utils::keccak256;
+use serde_json::Value;
+use std::sync::Arc;
+
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+use defi_hot_wallet::core::abi::{abi_pack, abi_word_address, selector_from_signature};
+
+const API_KEY: &str = "transaction-inspect-admin-key";
+const SESSION: &str = "transaction-inspect-session";
+const MANAGED: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+const TOKEN: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+const HASH: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+struct Harness {
+    app: TestServer,
+    mock: MockProvider,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    let client = EthereumClient::new_with_provider_and_chain(provider, "eth", 1);
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY))
+        .await
+        .with_chain_clients(ClientRegistry::new().with_client("eth", Arc::new(client)));
+
+    let user_id = util::sign_in(&server, "support@example.com", SESSION).await;
+    server.user_db.link_wallet(&user_id, "treasury", MANAGED, None).await.unwrap();
+
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, mock, _dir: dir }
+}
+
+fn address(s: &str) -> Address {
+    s.parse().unwrap()
+}
+
+/// USDC transfer 2.5 (6 decimals) from the managed wallet, mined in block 100
+fn mined_erc20_transfer(mock: &MockProvider) {
+    let input = abi_pack(
+        selector_from_signature("transfer(address,uint256)"),
+        &[abi_word_address(RECIPIENT).unwrap(), {
+            let mut word = [0u8; 32];
+            U256::from(2_500_000u64).to_big_endian(&mut word);
+            word
+        }],
+    );
+    let tx = Transaction {
+        hash: HASH.parse().unwrap(),
+        from: address(MANAGED),
+        to: Some(address(TOKEN)),
+        block_number: Some(U64::from(100)),
+        gas: U256::from(60_000u64),
+        gas_price: Some(U256::from(30_000_000_000u64)),
+        input: Bytes::from(input),
+        ..Default::default()
+    };
+    let mut amount = [0u8; 32];
+    U256::from(2_500_000u64).to_big_endian(&mut amount);
+    let receipt = TransactionReceipt {
+        transaction_hash: tx.hash,
+        block_number: Some(U64::from(100)),
+        status: Some(U64::from(1)),
+        gas_used: Some(U256::from(50_000u64)),
+        effective_gas_price: Some(U256::from(20_000_000_000u64)),
+        logs: vec![Log {
+            address: address(TOKEN),
+            topics: vec![
+                H256::from(keccak256("Transfer(address,address,uint256)")),
+                H256::from(address(MANAGED)),
+                H256::from(address(RECIPIENT)),
+            ],
+            data: Bytes::from(amount.to_vec()),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    // MockProvider 按后进先出应答：transaction → receipt → block number
+    mock.push(U64::from(104)).unwrap();
+    mock.push(receipt).unwrap();
+    mock.push(tx).unwrap();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_decoded_erc20_transfer() {
+    let h = build().await;
+    mined_erc20_transfer(&h.mock);
+
+    let res = h
+        .app
+        .get(&format!("/api/networks/eth/transactions/{}", HASH))
+        .add_header("Authorization", API_KEY)
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["from"], MANAGED);
+    assert_eq!(body["to"], TOKEN);
+    assert_eq!(body["value"], "0.000000000000000000");
+    assert_eq!(body["status"], "success");
+    assert_eq!(body["block_number"], 100);
+    assert_eq!(body["confirmations"], 5);
+    assert_eq!(body["gas_used"], "50000");
+    assert_eq!(body["effective_gas_price"], "20000000000");
+    assert_eq!(body["fee"], "0.001000000000000000");
+
+    assert_eq!(body["call"]["kind"], "decoded");
+    assert_eq!(body["call"]["method"], "transfer");
+    assert_eq!(body["call"]["params"][0]["name"], "to");
+    assert_eq!(body["call"]["params"][0]["value"], RECIPIENT);
+    assert_eq!(body["call"]["params"][1]["value"], "2500000");
+
+    let log = &body["logs"][0];
+    assert_eq!(log["event"], "Transfer");
+    assert_eq!(log["contract"], TOKEN);
+    assert_eq!(log["params"][0]["value"], MANAGED);
+    assert_eq!(log["params"][2]["value"], "2500000");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_unknown_selector_is_reported_raw() {
+    let h = build().await;
+    let tx = Transaction {
+        hash: HASH.parse().unwrap(),
+        from: address(RECIPIENT),
+        to: Some(address(TOKEN)),
+        value: U256::exp10(18),
+        input: Bytes::from(vec![0x12, 0x34, 0x56, 0x78, 0, 0, 0, 1]),
+        ..Default::default()
+    };
+    // pending：没有 receipt 查询
+    h.mock.push(U64::from(104)).unwrap();
+    h.mock.push(tx).unwrap();
+
+    let res = h
+        .app
+        .get(&format!("/api/networks/eth/transactions/{}", HASH))
+        .add_header("Authorization", API_KEY)
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["status"], "pending");
+    assert_eq!(body["confirmations"], 0);
+    assert_eq!(body["value"], "1.000000000000000000");
+    assert_eq!(body["symbol"], "ETH");
+    assert!(body["fee"].is_null());
+    assert_eq!(body["call"]["kind"], "unknown");
+    assert_eq!(body["call"]["selector"], "0x12345678");
+    assert_eq!(body["call"]["calldata_len"], 8);
+    assert_eq!(body["logs"].as_array().unwrap().len(), 0);
+    assert_eq!(body["managed"]["from"], false);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_unknown_hash_returns_404() {
+    let h = build().await;
+    h.mock.push::<Option<Transaction>, _>(None).unwrap();
+
+    let res = h
+        .app
+        .get(&format!("/api/networks/eth/transactions/{}", HASH))
+        .add_header("Authorization", API_KEY)
+        .await;
+    res.assert_status_not_found();
+    assert_eq!(res.json::<Value>()["error"]["code"], "TRANSACTION_NOT_FOUND");
+
+    // 无凭据
+    h.app.get(&format!("/api/networks/eth/transactions/{}", HASH)).await.assert_status_unauthorized();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_managed_address_flag_by_caller() {
+    let h = build().await;
+    mined_erc20_transfer(&h.mock);
+    mined_erc20_transfer(&h.mock);
+
+    let admin: Value = h
+        .app
+        .get(&format!("/api/networks/eth/transactions/{}", HASH))
+        .add_header("Authorization", API_KEY)
+        .await
+        .json();
+    assert_eq!(admin["managed"]["from"], true);
+    assert_eq!(admin["managed"]["from_wallet"], "treasury");
+    assert_eq!(admin["managed"]["to"], false);
+
+    let reader: Value = h
+        .app
+        .get(&format!("/api/networks/eth/transactions/{}", HASH))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await
+        .json();
+    assert_eq!(reader["managed"]["from"], true);
+    assert!(reader["managed"].get("from_wallet").is_none());
+    assert!(reader["managed"].get("to_wallet").is_none());
+}
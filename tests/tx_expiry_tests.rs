@@ -0,0 +1,232 @@
+//! pending 过期：超时且已不在 mempool 的transaction标记 expired、释放 nonce，迟到的确认补记账调整
+
+use async_trait::async_trait;
+use ethers::signers::LocalWallet;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionReceipt, TransactionRequest, H256, U256, U64};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use defi_hot_wallet::core::config::FeeTrackingConfig;
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::intents::{BroadcastChain, SendFingerprint, SigningIntentLog};
+use defi_hot_wallet::monitoring::WalletMetrics;
+use defi_hot_wallet::ops::fee_tracking::ConfirmationPoller;
+use defi_hot_wallet::ops::jobs::Job;
+use defi_hot_wallet::ops::tx_expiry::{ExpiryReport, PendingExpiry};
+use defi_hot_wallet::storage::{journal_events, WalletStorage, INTENT_RECORDED, INTENT_RELEASED};
+
+const WALLET: &str = "hot";
+const NETWORK: &str = "eth";
+const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+
+/// 节点：发送后入池，`drop` 移出 mempool 并退回 pending nonce，`mine` 之后才有回执
+#[derive(Default)]
+struct MockChain {
+    nonce: Mutex<u64>,
+    mempool: Mutex<HashSet<H256>>,
+    receipts: Mutex<HashMap<H256, TransactionReceipt>>,
+}
+
+impl MockChain {
+    fn drop_tx(&self, hash: &str) {
+        self.mempool.lock().unwrap().remove(&hash.parse::<H256>().unwrap());
+        *self.nonce.lock().unwrap() -= 1;
+    }
+
+    fn mine(&self, hash: &str) {
+        let hash: H256 = hash.parse().unwrap();
+        let receipt = TransactionReceipt {
+            transaction_hash: hash,
+            gas_used: Some(U256::from(21_000u64)),
+            effective_gas_price: Some(U256::from(1_000_000_000u64)),
+            status: Some(U64::from(1u64)),
+            ..Default::default()
+        };
+        self.receipts.lock().unwrap().insert(hash, receipt);
+    }
+}
+
+#[async_trait]
+impl BroadcastChain for MockChain {
+    async fn prepare(&self, _network: &str, tx: &mut TypedTransaction) -> Result<(), WalletError> {
+        let mut nonce = self.nonce.lock().unwrap();
+        tx.set_nonce(*nonce);
+        *nonce += 1;
+        tx.set_gas(21_000u64);
+        tx.set_gas_price(1_000_000_000u64);
+        tx.set_chain_id(1u64);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&self, _network: &str, raw: Bytes) -> Result<H256, WalletError> {
+        let hash = H256::from(ethers::utils::keccak256(&raw));
+        self.mempool.lock().unwrap().insert(hash);
+        Ok(hash)
+    }
+
+    async fn transaction_known(&self, _network: &str, tx_hash: H256) -> Result<bool, WalletError> {
+        Ok(self.mempool.lock().unwrap().contains(&tx_hash) || self.receipts.lock().unwrap().contains_key(&tx_hash))
+    }
+
+    async fn transaction_receipt(
+        &self,
+        _network: &str,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>, WalletError> {
+        Ok(self.receipts.lock().unwrap().get(&tx_hash).cloned())
+    }
+}
+
+struct Harness {
+    storage: Arc<WalletStorage>,
+    chain: Arc<MockChain>,
+    log: SigningIntentLog,
+    metrics: Arc<WalletMetrics>,
+    signer: LocalWallet,
+    /// transactions 按 wallets.id 记录
+    wallet_id: String,
+}
+
+impl Harness {
+    async fn new() -> Self {
+        // 落库wallet写审计记录，其 MAC 需要 WALLET_ENC_KEY
+        std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+        let storage = Arc::new(WalletStorage::new_with_url("sqlite::memory:").await.unwrap());
+        storage.store_wallet(WALLET, b"blob", false).await.unwrap();
+        let wallet_id = storage.wallet_id(WALLET).await.unwrap().unwrap();
+        let chain = Arc::new(MockChain::default());
+        let log = SigningIntentLog::new(storage.clone(), chain.clone());
+        let metrics = Arc::new(WalletMetrics::new().unwrap());
+        Self { storage, chain, log, metrics, signer: KEY.parse().unwrap(), wallet_id }
+    }
+
+    async fn send(&self, wei: u64) -> String {
+        let tx: TypedTransaction = TransactionRequest::new().to(RECIPIENT.parse::<Address>().unwrap()).value(wei).into();
+        self.log.send(WALLET, NETWORK, &self.signer, tx).await.unwrap()
+    }
+
+    /// `deadline` 为 0 时刚发出的transaction即已超时
+    fn expiry(&self, deadline: Duration) -> PendingExpiry {
+        PendingExpiry::new(self.storage.clone(), self.chain.clone())
+            .with_deadline(NETWORK, deadline)
+            .with_metrics(self.metrics.clone())
+    }
+
+    async fn status(&self, hash: &str) -> String {
+        self.storage.transaction_by_hash(hash).await.unwrap().unwrap().status
+    }
+
+    async fn journal_types(&self) -> Vec<String> {
+        self.storage.journal_events(0, 100).await.unwrap().into_iter().map(|e| e.event_type).collect()
+    }
+}
+
+fn fingerprint(wei: u64) -> SendFingerprint<'static> {
+    SendFingerprint {
+        wallet_name: WALLET,
+        network: NETWORK,
+        to: RECIPIENT.parse().unwrap(),
+        token: None,
+        amount: U256::from(wei),
+    }
+}
+
+#[tokio::test]
+async fn test_dropped_send_expires_after_deadline() {
+    let h = Harness::new().await;
+    let wei = 10u64.pow(17);
+    let hash = h.send(wei).await;
+    h.chain.drop_tx(&hash);
+
+    // 未到期限：不查链也不过期
+    let report = h.expiry(Duration::from_secs(3600)).run_once().await;
+    assert_eq!(report, ExpiryReport::default());
+    assert_eq!(h.status(&hash).await, "pending");
+    assert!(h.log.check_duplicate(&fingerprint(wei), false).await.is_err());
+
+    let report = h.expiry(Duration::ZERO).run_once().await;
+    assert_eq!(report, ExpiryReport { checked: 1, expired: 1, nonces_released: 1, ..Default::default() });
+    assert_eq!(h.status(&hash).await, "expired");
+    let tx = h.storage.transaction_by_hash(&hash).await.unwrap().unwrap();
+    let intent = h.storage.get_signing_intent(&tx.id).await.unwrap().unwrap();
+    assert_eq!(intent.state, INTENT_RELEASED);
+    assert_eq!(h.metrics.transactions_expired.with_label_values(&[NETWORK]).get(), 1);
+
+    let events = h.storage.journal_events(0, 100).await.unwrap();
+    let expired = events.iter().find(|e| e.event_type == journal_events::TRANSACTION_EXPIRED).unwrap();
+    assert_eq!(expired.entity_id, tx.id);
+    assert_eq!(expired.payload["tx_hash"], hash);
+    assert!(events.iter().any(|e| e.event_type == journal_events::TRANSACTION_STATUS_CHANGED
+        && e.payload["to_status"] == "expired"));
+
+    // 同一笔转账可以重试，且节点退回的 nonce 不再被占用
+    h.log.check_duplicate(&fingerprint(wei), false).await.unwrap();
+    let retry = h.send(wei + 1).await;
+    let retry_tx = h.storage.transaction_by_hash(&retry).await.unwrap().unwrap();
+    let retry_intent = h.storage.get_signing_intent(&retry_tx.id).await.unwrap().unwrap();
+    assert_eq!(retry_intent.nonce, intent.nonce);
+    assert_eq!(retry_intent.state, INTENT_RECORDED);
+
+    // 终态不再重复处理
+    assert_eq!(h.expiry(Duration::ZERO).run_once().await.expired, 0);
+    assert_eq!(h.status(&hash).await, "expired");
+}
+
+#[tokio::test]
+async fn test_send_still_in_mempool_or_mined_does_not_expire() {
+    let h = Harness::new().await;
+    let queued = h.send(1).await;
+    let mined = h.send(2).await;
+    h.chain.mine(&mined);
+    h.chain.mempool.lock().unwrap().remove(&mined.parse::<H256>().unwrap());
+
+    let report = h.expiry(Duration::ZERO).run_once().await;
+    assert_eq!(report, ExpiryReport { checked: 2, still_known: 2, ..Default::default() });
+    assert_eq!(h.status(&queued).await, "pending");
+    assert_eq!(h.status(&mined).await, "pending");
+    assert_eq!(h.metrics.transactions_expired.with_label_values(&[NETWORK]).get(), 0);
+    assert!(!h.journal_types().await.iter().any(|t| t == journal_events::TRANSACTION_EXPIRED));
+}
+
+#[tokio::test]
+async fn test_late_confirmation_resurrects_with_ledger_correction() {
+    let h = Harness::new().await;
+    let hash = h.send(5).await;
+    h.chain.drop_tx(&hash);
+
+    // 过期在确认轮询的同一周期内运行
+    let poller = ConfirmationPoller::new(FeeTrackingConfig::default(), h.storage.clone(), h.chain.clone())
+        .with_expiry(h.expiry(Duration::ZERO));
+    poller.run(CancellationToken::new()).await.unwrap();
+    assert_eq!(h.status(&hash).await, "expired");
+    assert!(h.storage.ledger_corrections(Some(&h.wallet_id), 10).await.unwrap().is_empty());
+
+    // 原始transaction被重新广播并打包
+    h.chain.mine(&hash);
+    let report = poller.run_once().await;
+    assert_eq!(report.confirmed, 1);
+    let tx = h.storage.transaction_by_hash(&hash).await.unwrap().unwrap();
+    assert_eq!(tx.status, "confirmed");
+    assert!(tx.confirmed_at.is_some());
+
+    let corrections = h.storage.ledger_corrections(Some(&h.wallet_id), 10).await.unwrap();
+    assert_eq!(corrections.len(), 1);
+    let correction = &corrections[0];
+    assert_eq!(correction.transaction_id, tx.id);
+    assert_eq!((correction.from_status.as_str(), correction.to_status.as_str()), ("expired", "confirmed"));
+    assert_eq!(correction.amount, tx.amount);
+    assert!(h.storage.ledger_corrections(Some("other"), 10).await.unwrap().is_empty());
+
+    let events = h.storage.journal_events(0, 100).await.unwrap();
+    let corrected = events.iter().find(|e| e.event_type == journal_events::LEDGER_CORRECTED).unwrap();
+    assert_eq!(corrected.payload["correction_id"], correction.id);
+    assert_eq!(corrected.payload["to_status"], "confirmed");
+
+    // 已确认后再次确认不产生新的调整
+    h.storage.update_transaction_status(&tx.id, "confirmed", None).await.unwrap();
+    assert_eq!(h.storage.ledger_corrections(None, 10).await.unwrap().len(), 1);
+}
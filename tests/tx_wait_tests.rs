@@ -0,0 +1,203 @@
+//! `GET /api/transactions/:id/wait`：长轮询、超时与 SSE，以及并发等待共享轮询（MockProvider）
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use ethers::providers::{MockProvider, Provider};
+use ethers::types::{Address, Transaction, TransactionReceipt, U256, U64};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+use defi_hot_wallet::blockchain::tx_watch::TxWatchRegistry;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "tx-wait-admin-key";
+const HASH: &str = "0x2222222222222222222222222222222222222222222222222222222222222222";
+const POLL: Duration = Duration::from_millis(20);
+
+struct Harness {
+    app: TestServer,
+    server: WalletServer,
+    mock: MockProvider,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    let client = EthereumClient::new_with_provider_and_chain(provider, "eth", 1);
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap()
+    .with_chain_clients(ClientRegistry::new().with_client("eth", Arc::new(client)))
+    .with_tx_watches(TxWatchRegistry::new(POLL));
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { app, server, mock, _dir: dir }
+}
+
+/// What the node answers to one poll
+enum Poll {
+    Pending,
+    Mined { block: u64, head: u64 },
+}
+
+fn transaction(block: Option<u64>) -> Transaction {
+    Transaction {
+        hash: HASH.parse().unwrap(),
+        from: Address::repeat_byte(0x11),
+        to: Some(Address::repeat_byte(0x42)),
+        value: U256::exp10(17),
+        block_number: block.map(U64::from),
+        ..Default::default()
+    }
+}
+
+impl Harness {
+    /// MockProvider 后进先出：按轮询顺序排好后倒序压入
+    fn script(&self, polls: &[Poll]) {
+        let mut responses: Vec<Value> = Vec::new();
+        for poll in polls {
+            match *poll {
+                Poll::Pending => {
+                    responses.push(serde_json::to_value(transaction(None)).unwrap());
+                    responses.push(serde_json::to_value(U64::from(99)).unwrap());
+                }
+                Poll::Mined { block, head } => {
+                    let receipt = TransactionReceipt {
+                        transaction_hash: HASH.parse().unwrap(),
+                        block_number: Some(U64::from(block)),
+                        status: Some(U64::from(1)),
+                        gas_used: Some(U256::from(21_000u64)),
+                        effective_gas_price: Some(U256::from(10_000_000_000u64)),
+                        ..Default::default()
+                    };
+                    responses.push(serde_json::to_value(transaction(Some(block))).unwrap());
+                    responses.push(serde_json::to_value(receipt).unwrap());
+                    responses.push(serde_json::to_value(U64::from(head)).unwrap());
+                }
+            }
+        }
+        for response in responses.into_iter().rev() {
+            self.mock.push(response).unwrap();
+        }
+    }
+
+    async fn wait(&self, query: &str) -> (StatusCode, Value) {
+        let res = self
+            .app
+            .get(&format!("/api/transactions/{}/wait?{}", HASH, query))
+            .add_header("Authorization", API_KEY)
+            .await;
+        (res.status_code(), res.json())
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_confirmation_on_third_poll_returns_early() {
+    let h = build().await;
+    h.script(&[Poll::Pending, Poll::Pending, Poll::Mined { block: 100, head: 102 }]);
+
+    let (status, body) = h.wait("network=eth&timeout=10&confirmations=3").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "confirmed");
+    assert_eq!(body["confirmations"], 3);
+    assert_eq!(body["required_confirmations"], 3);
+    assert_eq!(body["block_number"], 100);
+    assert_eq!(body["receipt"]["status"], "success");
+    assert_eq!(body["receipt"]["gas_used"], "21000");
+    assert_eq!(body["timed_out"], false);
+    assert!(body["elapsed_ms"].as_u64().unwrap() < 2_000, "{}", body);
+
+    let res = h.app.get(&format!("/api/transactions/{}/wait", HASH)).await;
+    res.assert_status_unauthorized();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_timeout_returns_latest_known_state() {
+    let h = build().await;
+    // 确认数不够；之后 MockProvider 没有应答，保留最后状态
+    h.script(&[Poll::Pending, Poll::Mined { block: 100, head: 100 }]);
+
+    let (status, body) = h.wait("network=eth&timeout=1&confirmations=12").await;
+    assert_eq!(status, StatusCode::REQUEST_TIMEOUT, "{}", body);
+    assert_eq!(body["status"], "confirming");
+    assert_eq!(body["confirmations"], 1);
+    assert_eq!(body["required_confirmations"], 12);
+    assert_eq!(body["timed_out"], true);
+    assert!(body["elapsed_ms"].as_u64().unwrap() >= 1_000);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_event_stream_sequence() {
+    let h = build().await;
+    h.script(&[
+        Poll::Pending,
+        Poll::Pending,
+        Poll::Mined { block: 100, head: 100 },
+        Poll::Mined { block: 100, head: 102 },
+    ]);
+
+    let res = h
+        .app
+        .get(&format!("/api/transactions/{}/wait?network=eth&timeout=10&confirmations=3", HASH))
+        .add_header("Authorization", API_KEY)
+        .add_header("Accept", "text/event-stream")
+        .await;
+    res.assert_status_ok();
+    let text = res.text();
+    let events: Vec<&str> = text.lines().filter_map(|l| l.strip_prefix("event: ")).collect();
+    // 重复的 pending 不会再推一次
+    assert_eq!(events, ["pending", "confirming", "confirmed"], "{}", text);
+    let data: Vec<Value> = text
+        .lines()
+        .filter_map(|l| l.strip_prefix("data: "))
+        .map(|d| serde_json::from_str(d).unwrap())
+        .collect();
+    assert_eq!(data[1]["confirmations"], 1);
+    assert_eq!(data[2]["confirmations"], 3);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_concurrent_waiters_share_one_poller() {
+    let h = build().await;
+    // 两个轮询循环会各自消耗应答，谁都等不到确认
+    h.script(&[Poll::Pending, Poll::Pending, Poll::Pending, Poll::Mined { block: 100, head: 102 }]);
+
+    let (first, second) = tokio::join!(
+        h.wait("network=eth&timeout=10&confirmations=3"),
+        h.wait("network=eth&timeout=10&confirmations=2"),
+    );
+    assert_eq!(first.0, StatusCode::OK, "{}", first.1);
+    assert_eq!(second.0, StatusCode::OK, "{}", second.1);
+    assert_eq!(first.1["confirmations"], 3);
+    assert_eq!(second.1["status"], "confirmed");
+    assert_eq!(h.server.tx_watches.pollers_started(), 1);
+
+    // 等待方都离开后，轮询任务退出并从注册表移除
+    tokio::time::sleep(POLL * 5).await;
+    assert_eq!(h.server.tx_watches.active(), 0);
+}
@@ -0,0 +1,314 @@
+//! user数据擦除（`POST /api/admin/users/:id/erase`）集成测试
+//!
+//! 余额查询使用 MockProvider 支撑的 EthereumClient（响应按 LIFO 弹出）。
+
+mod util;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use ethers::providers::{MockProvider, Provider};
+use ethers::types::U256;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::api::user_db::{CreateUserRequest, ERASED_OWNER_ID};
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+use defi_hot_wallet::storage::{
+    hash_token, journal_events, NewWalletToken, WalletCapability, AUTH_MODE_BEARER, ERASED_MARKER,
+};
+
+const API_KEY: &str = "user-erasure-admin-key";
+const SESSION: &str = "user-erasure-session";
+const WALLET_TOKEN: &str = "wt_user_erasure_token";
+/// base64 of `[0x42; 32]`; storage needs it for audit log MACs
+const KEY_B64: &str = "QkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkI=";
+const TREASURY: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+const PAYROLL: &str = "0x000000000000000000000000000000000000bEEF";
+const IP: &str = "203.0.113.7";
+const UA: &str = "Mozilla/5.0 (X11; Linux x86_64)";
+
+struct Harness {
+    server: WalletServer,
+    app: TestServer,
+    mock: MockProvider,
+    user_id: String,
+    _dir: tempfile::TempDir,
+}
+
+async fn create_user(server: &WalletServer, email: &str) -> String {
+    server
+        .user_db
+        .create_user(CreateUserRequest {
+            email: email.to_string(),
+            password: "Er4sure!Secure#2024".to_string(),
+            username: Some("Erin Example".to_string()),
+        })
+        .await
+        .unwrap()
+        .id
+}
+
+/// 一个带两个wallet、会话、偏好、wallet token 和带 IP/UA 审计记录的user
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("WALLET_ENC_KEY", KEY_B64);
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    let client = EthereumClient::new_with_provider_and_chain(provider, "eth", 1);
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY))
+        .await
+        .with_chain_clients(ClientRegistry::new().with_client("eth", Arc::new(client)));
+
+    // users.db 只自动执行 001 迁移；补上偏好表
+    sqlx::query(include_str!("../migrations/004_create_user_preferences.sql"))
+        .execute(server.user_db.pool())
+        .await
+        .unwrap();
+
+    let user_id = create_user(&server, "erin@example.com").await;
+    server.user_db.link_wallet(&user_id, "treasury", TREASURY, None).await.unwrap();
+    server.user_db.link_wallet(&user_id, "payroll", PAYROLL, None).await.unwrap();
+    server.session_store.register_token(SESSION, &user_id, 3600).await;
+    sqlx::query(
+        "INSERT INTO sessions (user_id, token, expires_at, ip_address, user_agent) \
+         VALUES (?, 'persisted-session', datetime('now', '+1 day'), ?, ?)",
+    )
+    .bind(&user_id)
+    .bind(IP)
+    .bind(UA)
+    .execute(server.user_db.pool())
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO user_preferences (user_id, two_fa_enabled, updated_at, created_at) VALUES (?, 1, 0, 0)")
+        .bind(&user_id)
+        .execute(server.user_db.pool())
+        .await
+        .unwrap();
+
+    let (wallet_id, _) = server.user_db.find_user_wallet(&user_id, "treasury").await.unwrap().unwrap();
+    server
+        .storage
+        .insert_wallet_token(&NewWalletToken {
+            id: "wt-erasure",
+            token_hash: &hash_token(WALLET_TOKEN),
+            wallet_id,
+            wallet_name: "treasury",
+            owner_user_id: &user_id,
+            capabilities: WalletCapability::ReadBalance.bit(),
+            amount_cap: None,
+            expires_at: i64::MAX,
+            created_by: &user_id,
+            auth_mode: AUTH_MODE_BEARER,
+            signing_secret: None,
+        })
+        .await
+        .unwrap();
+
+    for wallet in ["treasury", "payroll"] {
+        server.storage.log_action(wallet, "login", "{\"ok\":true}", Some(IP), Some(UA)).await.unwrap();
+    }
+    server.storage.log_action("treasury", "wallet_token.use", "{}", None, None).await.unwrap();
+    // 其他user的wallet不受影响
+    server.storage.log_action("bystander", "login", "{}", Some(IP), Some(UA)).await.unwrap();
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { server, app, mock, user_id, _dir: dir }
+}
+
+/// 每个wallet address在 eth 上查询一次：payroll 的响应先压入（后弹出）
+fn push_balances(mock: &MockProvider, treasury_wei: u64, payroll_wei: u64) {
+    mock.push(U256::from(payroll_wei)).unwrap();
+    mock.push(U256::from(treasury_wei)).unwrap();
+}
+
+async fn erase(h: &Harness, user_id: &str) -> axum_test::TestResponse {
+    h.app
+        .post(&format!("/api/admin/users/{}/erase", user_id))
+        .add_header("Authorization", API_KEY)
+        .json(&json!({ "requested_by": "dpo@example.com" }))
+        .await
+}
+
+async fn count(h: &Harness, sql: &str) -> i64 {
+    sqlx::query_scalar(sql).bind(&h.user_id).fetch_one(h.server.user_db.pool()).await.unwrap()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_full_erasure_of_seeded_user() {
+    let h = build().await;
+    push_balances(&h.mock, 0, 0);
+
+    let res = erase(&h, &h.user_id).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["changed"], true);
+
+    // profile 已匿名化且无法登录
+    let user = h.server.user_db.get_user_by_id(&h.user_id).await.unwrap();
+    assert_eq!(user.email, format!("erased-{}@erased.invalid", h.user_id));
+    assert_eq!(user.username, None);
+    assert!(!user.is_active);
+    assert!(h.server.user_db.user_exists(ERASED_OWNER_ID).await.unwrap());
+
+    // wallet 转移给系统 erased owner
+    assert!(h.server.user_db.get_user_wallets(&h.user_id).await.unwrap().is_empty());
+    let mut moved = h.server.user_db.get_user_wallets(ERASED_OWNER_ID).await.unwrap();
+    moved.sort();
+    assert_eq!(moved, vec!["payroll".to_string(), "treasury".to_string()]);
+
+    // 会话、偏好、wallet token 均已删除
+    assert_eq!(count(&h, "SELECT COUNT(*) FROM sessions WHERE user_id = ?").await, 0);
+    assert_eq!(count(&h, "SELECT COUNT(*) FROM user_preferences WHERE user_id = ?").await, 0);
+    assert!(h.server.session_store.validate_token(SESSION).await.is_err());
+    assert!(h.server.storage.find_wallet_token(WALLET_TOKEN).await.unwrap().is_none());
+
+    // 审计记录：IP/UA 被替换，其余内容保留，其他wallet不变
+    let treasury = h.server.storage.get_audit_logs(Some("treasury")).await.unwrap();
+    let login = treasury.iter().find(|l| l.action == "login").unwrap();
+    assert_eq!(login.ip_address.as_deref(), Some(ERASED_MARKER));
+    assert_eq!(login.user_agent.as_deref(), Some(ERASED_MARKER));
+    assert_eq!(login.details.as_deref(), Some("{\"ok\":true}"));
+    let untouched = treasury.iter().find(|l| l.action == "wallet_token.use").unwrap();
+    assert_eq!(untouched.ip_address, None);
+    let bystander = h.server.storage.get_audit_logs(Some("bystander")).await.unwrap();
+    assert_eq!(bystander[0].ip_address.as_deref(), Some(IP));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_audit_log_verifies_after_remac() {
+    let h = build().await;
+    push_balances(&h.mock, 0, 0);
+    let before = h.server.storage.get_audit_logs(None).await.unwrap();
+
+    erase(&h, &h.user_id).await.assert_status_ok();
+
+    // 每一行（包括改写过的）都通过 MAC 校验
+    let after = h.server.storage.get_audit_logs(None).await.unwrap();
+    assert_eq!(after.len(), before.len());
+    let redacted: Vec<i64> =
+        after.iter().filter(|l| l.ip_address.as_deref() == Some(ERASED_MARKER)).map(|l| l.id).collect();
+    assert_eq!(redacted.len(), 2);
+
+    // re-MAC 作为单独事件记入 journal，列出改写的行
+    let events = h.server.storage.journal_events(0, 100).await.unwrap();
+    let remac = events.iter().find(|e| e.event_type == journal_events::AUDIT_REMACED).unwrap();
+    assert_eq!(remac.entity_type, "user");
+    assert_eq!(remac.entity_id, h.user_id);
+    let mut ids: Vec<i64> = serde_json::from_value(remac.payload["audit_ids"].clone()).unwrap();
+    ids.sort();
+    let mut expected = redacted.clone();
+    expected.sort();
+    assert_eq!(ids, expected);
+    let erased = events.iter().find(|e| e.event_type == journal_events::USER_ERASED).unwrap();
+    assert!(erased.seq > remac.seq);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_nonzero_balance_blocks_erasure() {
+    let h = build().await;
+    push_balances(&h.mock, 0, 1_500_000_000_000_000_000);
+
+    let res = erase(&h, &h.user_id).await;
+    res.assert_status(StatusCode::CONFLICT);
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "WALLET_NOT_EMPTY");
+    let error = body["error"]["message"].as_str().unwrap();
+    assert!(error.contains("'payroll' holds 1.5"), "{}", error);
+    assert!(!error.contains("treasury"), "{}", error);
+
+    // 什么都没有改动
+    assert!(h.server.user_db.get_user_by_id(&h.user_id).await.unwrap().is_active);
+    assert_eq!(h.server.user_db.get_user_wallets(&h.user_id).await.unwrap().len(), 2);
+    assert!(h.server.session_store.validate_token(SESSION).await.is_ok());
+    assert!(h.server.storage.find_wallet_token(WALLET_TOKEN).await.unwrap().is_some());
+    let logs = h.server.storage.get_audit_logs(Some("payroll")).await.unwrap();
+    assert_eq!(logs[0].ip_address.as_deref(), Some(IP));
+    assert!(h.server.storage.erasure_certificates(&h.user_id).await.unwrap().is_empty());
+
+    // 清空后可以擦除
+    push_balances(&h.mock, 0, 0);
+    erase(&h, &h.user_id).await.assert_status_ok();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_rerun_is_idempotent() {
+    let h = build().await;
+    push_balances(&h.mock, 0, 0);
+    let first: Value = erase(&h, &h.user_id).await.json();
+    let seq = h.server.storage.journal_events(0, 100).await.unwrap().last().unwrap().seq;
+
+    // wallet 已转走，第二次不再查询余额
+    let res = erase(&h, &h.user_id).await;
+    res.assert_status_ok();
+    let second: Value = res.json();
+    assert_eq!(second["changed"], false);
+    assert_eq!(second["certificate"], first["certificate"]);
+
+    assert_eq!(h.server.storage.journal_events(0, 100).await.unwrap().last().unwrap().seq, seq);
+    assert_eq!(h.server.storage.erasure_certificates(&h.user_id).await.unwrap().len(), 1);
+    assert_eq!(h.server.user_db.get_user_wallets(ERASED_OWNER_ID).await.unwrap().len(), 2);
+
+    // 另一个user的同名wallet转移时加后缀，不与 erased owner 已有的冲突
+    let other = create_user(&h.server, "olga@example.com").await;
+    h.server.user_db.link_wallet(&other, "treasury", PAYROLL, None).await.unwrap();
+    h.mock.push(U256::zero()).unwrap();
+    erase(&h, &other).await.assert_status_ok();
+    let names = h.server.user_db.get_user_wallets(ERASED_OWNER_ID).await.unwrap();
+    assert_eq!(names.len(), 3);
+    assert!(names.iter().any(|n| n.starts_with("treasury#")), "{:?}", names);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_certificate_content() {
+    let h = build().await;
+    push_balances(&h.mock, 0, 0);
+
+    h.app
+        .post(&format!("/api/admin/users/{}/erase", h.user_id))
+        .await
+        .assert_status_unauthorized();
+    let res = h
+        .app
+        .post("/api/admin/users/no-such-user/erase")
+        .add_header("Authorization", API_KEY)
+        .await;
+    res.assert_status_not_found();
+    assert_eq!(res.json::<Value>()["error"]["code"], "USER_NOT_FOUND");
+
+    let cert = erase(&h, &h.user_id).await.json::<Value>()["certificate"].clone();
+    assert_eq!(cert["user_id"], h.user_id);
+    assert_eq!(cert["erased_by"], "dpo@example.com");
+    assert_eq!(
+        cert["categories"],
+        json!({
+            "api_tokens": 1,
+            "audit_log_network_identifiers": 2,
+            "preferences": 1,
+            "profile": 1,
+            "sessions": 2,
+            "wallet_links": 2,
+            "wallet_ownership": 0,
+        })
+    );
+    let mut wallets: Vec<String> = serde_json::from_value(cert["wallets"].clone()).unwrap();
+    wallets.sort();
+    assert_eq!(wallets, vec!["payroll".to_string(), "treasury".to_string()]);
+    assert!(cert["created_at"].as_i64().unwrap() > 0);
+
+    // 之后仍可取回作为证明
+    let res = h
+        .app
+        .get(&format!("/api/admin/users/{}/erasure-certificates", h.user_id))
+        .add_header("Authorization", API_KEY)
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["user_id"], h.user_id);
+    assert_eq!(body["certificates"], json!([cert]));
+}
@@ -1,7 +1,21 @@
 // tests/util.rs
 // Shared test helpers for integration/unit tests
+// Each test crate uses only some of them.
+#![allow(dead_code)]
 
+use std::path::Path;
 use std::process::Command;
+use std::sync::Arc;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::api::user_db::CreateUserRequest;
+use defi_hot_wallet::core::clock::Clock;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::core::ids::IdGenerator;
+use defi_hot_wallet::security::SecretVec;
+
+/// Login password of the users registered by [`sign_in`]
+pub const USER_PASSWORD: &str = "Integr4tion!Login#2024";
 
 /// Sets a deterministic, test-only environment for tests that need
 /// WALLET_ENC_KEY, TEST_SKIP_DECRYPT and ALLOW_BRIDGE_MOCKS.
@@ -18,7 +32,6 @@ pub fn set_test_env() {
 }
 
 /// Example helper to spawn the CLI with test env applied (returns std::process::Child)
-#[allow(dead_code)]
 pub fn spawn_cli_with_test_env(args: &[&str]) -> std::process::Child {
     set_test_env();
     let mut cmd = Command::new("cargo");
@@ -29,3 +42,106 @@ pub fn spawn_cli_with_test_env(args: &[&str]) -> std::process::Child {
     // inherit current env which includes test env vars
     cmd.spawn().expect("failed to spawn wallet-cli")
 }
+
+/// Wallet config on an in-memory SQLite wallet database
+pub fn memory_config() -> WalletConfig {
+    WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    }
+}
+
+/// Wallet config on `dir/wallet.db`, so other storages opened on the same URL see the same data
+pub fn file_config(dir: &Path) -> WalletConfig {
+    let mut config = memory_config();
+    config.storage.database_url = format!("sqlite://{}?mode=rwc", dir.join("wallet.db").display());
+    config
+}
+
+/// `WalletServer::new_for_test` with its users.db under `dir` (through `USERS_DATABASE_URL`)
+/// and the non-custodial address column migrated in; `api_key` guards the admin routes
+pub async fn test_server(dir: &Path, config: WalletConfig, api_key: Option<&str>) -> WalletServer {
+    use_users_db(dir);
+    let server =
+        WalletServer::new_for_test("127.0.0.1".to_string(), 0, config, api_key.map(secret), None).await.unwrap();
+    migrate_users_db(&server).await;
+    server
+}
+
+/// [`test_server`] with an injected clock and id generator
+pub async fn test_server_with_sources(
+    dir: &Path,
+    config: WalletConfig,
+    api_key: Option<&str>,
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGenerator>,
+) -> WalletServer {
+    use_users_db(dir);
+    let server = WalletServer::new_for_test_with_sources(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        api_key.map(secret),
+        None,
+        clock,
+        ids,
+    )
+    .await
+    .unwrap();
+    migrate_users_db(&server).await;
+    server
+}
+
+/// Registers `email` (password [`USER_PASSWORD`]) and accepts `token` as its bearer token for an hour;
+/// returns the user id
+pub async fn sign_in(server: &WalletServer, email: &str, token: &str) -> String {
+    let user = server
+        .user_db
+        .create_user(CreateUserRequest {
+            email: email.to_string(),
+            password: USER_PASSWORD.to_string(),
+            username: None,
+        })
+        .await
+        .unwrap();
+    server.session_store.register_token(token, &user.id, 3600).await;
+    user.id
+}
+
+/// Creates `name` in the wallet manager and stores it, as `POST /api/admin/wallets` does;
+/// recorded transactions need the stored wallet. Returns its id
+pub async fn create_wallet(server: &WalletServer, name: &str, password: &str) -> String {
+    server.wallet_manager.create_wallet(name, password, false).await.unwrap();
+    let wallet = server.wallet_manager.get_wallet_by_name(name).await.unwrap().unwrap();
+    server.storage.store_wallet(name, &bincode::serialize(&wallet).unwrap(), false).await.unwrap();
+    server.storage.wallet_id(name).await.unwrap().unwrap()
+}
+
+fn use_users_db(dir: &Path) {
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.join("users.db").display()));
+}
+
+// users.db only runs migration 001 on its own; a reopened users.db already has the columns
+async fn migrate_users_db(server: &WalletServer) {
+    let migrated: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('user_wallets') WHERE name = 'wallet_address'",
+    )
+    .fetch_one(server.user_db.pool())
+    .await
+    .unwrap();
+    if !migrated {
+        sqlx::query(include_str!("../migrations/005_add_wallet_address.sql"))
+            .execute(server.user_db.pool())
+            .await
+            .unwrap();
+    }
+}
+
+fn secret(key: &str) -> SecretVec {
+    SecretVec::new(key.as_bytes().to_vec())
+}
@@ -0,0 +1,196 @@
+//! SQLite WAL checkpoint 与数据库大小监控：TRUNCATE 回收 WAL、防并发、metrics 与 admin 接口
+//!
+//! 使用临时目录中的文件数据库（内存库没有 `-wal` 文件）。
+
+use std::sync::Arc;
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{BackupConfig, BackupSinkConfig, StorageConfig, WalConfig, WalletConfig};
+use defi_hot_wallet::monitoring::WalletMetrics;
+use defi_hot_wallet::ops::db_backup::{BackupScheduler, FilesystemSink};
+use defi_hot_wallet::ops::maintenance::MaintenanceMode;
+use defi_hot_wallet::ops::wal::{CheckpointError, WalMaintenance};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{WalSettings, WalletStorage};
+
+const API_KEY: &str = "wal-maintenance-test-key-0123456789";
+/// base64 of `[0x42; 32]`; storage needs it for audit log MACs
+const KEY_B64: &str = "QkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkI=";
+/// 每条审计记录的 details 大小
+const ROW_BYTES: usize = 2048;
+
+struct Fixture {
+    dir: TempDir,
+    storage: Arc<WalletStorage>,
+    /// 同一数据库的另一个连接池：写入留下的连接不会计入 `storage` 的繁忙写连接
+    writer: WalletStorage,
+    metrics: Arc<WalletMetrics>,
+}
+
+impl Fixture {
+    /// 关闭自动 checkpoint，WAL 只会增长，直到显式 checkpoint
+    async fn new() -> Self {
+        std::env::set_var("WALLET_ENC_KEY", KEY_B64);
+        let dir = TempDir::new().unwrap();
+        let db_url = format!("sqlite://{}?mode=rwc", dir.path().join("wallets.db").display());
+        let settings = WalSettings { journal_size_limit: None, wal_autocheckpoint: Some(0) };
+        let storage = Arc::new(WalletStorage::new_with_wal(&db_url, settings).await.unwrap());
+        let writer = WalletStorage::new_with_wal(&db_url, settings).await.unwrap();
+        Self { dir, storage, writer, metrics: Arc::new(WalletMetrics::new().unwrap()) }
+    }
+
+    fn maintenance(&self) -> WalMaintenance {
+        WalMaintenance::new(WalConfig::default(), self.storage.clone(), self.metrics.clone())
+    }
+
+    async fn write_burst(&self, rows: usize) {
+        let details = "x".repeat(ROW_BYTES);
+        for i in 0..rows {
+            self.writer.log_action(&format!("wallet-{}", i), "wal_burst", &details, None, None).await.unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_checkpoint_truncates_wal_after_write_burst() {
+    let fx = Fixture::new().await;
+    fx.write_burst(200).await;
+
+    let before = fx.storage.database_file_stats().await.unwrap();
+    assert!(before.path.ends_with("wallets.db"), "{:?}", before.path);
+    assert!(before.wal_bytes >= (200 * ROW_BYTES) as u64, "{:?}", before);
+
+    let report = fx.maintenance().try_checkpoint(false).await.unwrap();
+    assert!(!report.deferred);
+    assert_eq!(report.attempts, 1);
+    let outcome = report.outcome.unwrap();
+    assert!(!outcome.busy);
+    // TRUNCATE 成功后 WAL 头已重置，报告的帧数均为 0
+    assert_eq!((outcome.log_frames, outcome.checkpointed_frames), (0, 0));
+    assert_eq!(report.wal_bytes_before, before.wal_bytes);
+    // TRUNCATE 把 WAL 截为 0，数据全部写回主库
+    assert_eq!(report.stats.wal_bytes, 0);
+    assert_eq!(fx.storage.database_file_stats().await.unwrap().wal_bytes, 0);
+    assert!(report.stats.db_bytes >= (200 * ROW_BYTES) as u64);
+}
+
+#[tokio::test]
+async fn test_concurrent_runs_are_refused() {
+    let fx = Fixture::new().await;
+    fx.write_burst(20).await;
+
+    // 第一个运行在第一次查询处让出，此时第二个拿不到锁
+    let wal = fx.maintenance();
+    let (first, second) = tokio::join!(wal.try_checkpoint(true), wal.try_checkpoint(true));
+    assert!(first.is_ok());
+    assert!(matches!(second, Err(CheckpointError::InProgress)));
+    // 锁已释放
+    wal.try_checkpoint(true).await.unwrap();
+    assert_eq!(fx.metrics.wal_checkpoints.with_label_values(&["completed"]).get(), 2);
+
+    // vacuum-into 与备份共用一把锁
+    let backups = BackupScheduler::new(
+        BackupConfig {
+            sink: BackupSinkConfig::Filesystem { directory: fx.dir.path().join("backups") },
+            ..Default::default()
+        },
+        fx.storage.clone(),
+        Arc::new(FilesystemSink::new(fx.dir.path().join("backups"))),
+        Arc::new(MaintenanceMode::new()),
+        fx.metrics.clone(),
+    )
+    .with_key([0x42; 32]);
+    let (run, snapshot) = tokio::join!(backups.try_run(), backups.try_vacuum_into("snapshot.db"));
+    assert!(run.is_ok());
+    assert!(snapshot.is_err());
+
+    let report = backups.try_vacuum_into("snapshot.db").await.unwrap();
+    assert!(report.verified, "{:?}", report);
+    assert!(fx.dir.path().join("backups").join("snapshot.db").exists());
+    // 快照不记入备份历史
+    assert_eq!(fx.storage.backup_history(10).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_metrics_reflect_write_volume() {
+    let fx = Fixture::new().await;
+    let wal = fx.maintenance();
+    let empty = wal.sample().await.unwrap();
+
+    fx.write_burst(100).await;
+    let grown = wal.sample().await.unwrap();
+    assert_eq!(fx.metrics.database_wal_bytes.get(), grown.wal_bytes as f64);
+    assert!(grown.wal_bytes >= empty.wal_bytes + (100 * ROW_BYTES) as u64);
+
+    let report = wal.try_checkpoint(false).await.unwrap();
+    let stats = &report.stats;
+    // 数据写回主库后页数至少增加写入量对应的页
+    let written_pages = (100 * ROW_BYTES) as i64 / stats.page_size;
+    assert!(stats.page_count >= empty.page_count + written_pages, "{:?} vs {:?}", stats, empty);
+    assert_eq!(fx.metrics.database_page_count.get(), stats.page_count as f64);
+    assert_eq!(fx.metrics.database_file_bytes.get(), stats.db_bytes as f64);
+    assert_eq!(fx.metrics.database_wal_bytes.get(), 0.0);
+    assert_eq!(fx.metrics.database_freelist_count.get(), stats.freelist_count as f64);
+    assert_eq!(fx.metrics.wal_checkpoints.with_label_values(&["completed"]).get(), 1);
+}
+
+#[tokio::test]
+async fn test_admin_db_endpoints() {
+    let dir = TempDir::new().unwrap();
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: format!("sqlite://{}?mode=rwc", dir.path().join("wallets.db").display()),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        backups: BackupConfig {
+            sink: BackupSinkConfig::Filesystem { directory: dir.path().join("backups") },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    std::env::set_var("WALLET_ENC_KEY", KEY_B64);
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    app.post("/api/admin/db/checkpoint").await.assert_status_unauthorized();
+    app.post("/api/admin/db/vacuum-into").json(&json!({ "object_key": "snap.db" })).await.assert_status_unauthorized();
+
+    let res = app.post("/api/admin/db/checkpoint").add_header("Authorization", API_KEY).await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["deferred"], false);
+    assert_eq!(body["outcome"]["busy"], false, "{}", body);
+    assert_eq!(body["stats"]["wal_bytes"], 0);
+
+    let res = app
+        .post("/api/admin/db/vacuum-into")
+        .add_header("Authorization", API_KEY)
+        .json(&json!({ "object_key": "../escape.db" }))
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_OBJECT_KEY");
+
+    let res = app
+        .post("/api/admin/db/vacuum-into")
+        .add_header("Authorization", API_KEY)
+        .json(&json!({ "object_key": "snap.db" }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["verified"], true, "{}", body);
+    assert!(dir.path().join("backups").join("snap.db").exists());
+}
@@ -0,0 +1,98 @@
+//! wallet元数据缓存的跨实例失效（`cache_epochs`）测试
+//!
+//! 两个 WalletStorage 句柄共享同一个内存数据库，模拟两个实例；时间由 FixedClock 推进。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use defi_hot_wallet::core::clock::FixedClock;
+use defi_hot_wallet::storage::{WalletCacheSettings, WalletStorage};
+
+const NOW: i64 = 1_700_000_000;
+
+struct Pair {
+    a: WalletStorage,
+    b: WalletStorage,
+    clock: Arc<FixedClock>,
+    url: String,
+}
+
+fn names(list: &[defi_hot_wallet::storage::WalletMetadata]) -> Vec<&str> {
+    list.iter().map(|w| w.name.as_str()).collect()
+}
+
+async fn open(url: &str, clock: &Arc<FixedClock>) -> WalletStorage {
+    // wallet创建会写 audit 记录，其 MAC 需要 WALLET_ENC_KEY
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let settings = WalletCacheSettings { ttl: Duration::from_secs(30), epoch_check_interval: Duration::from_secs(1) };
+    WalletStorage::new_with_url(url).await.unwrap().with_clock(clock.clone()).with_wallet_cache(settings)
+}
+
+async fn two_instances() -> Pair {
+    let url = format!("sqlite://file:wallet-cache-{}?mode=memory&cache=shared", uuid::Uuid::new_v4());
+    let clock = Arc::new(FixedClock::at(NOW));
+    let a = open(&url, &clock).await;
+    let b = open(&url, &clock).await;
+    Pair { a, b, clock, url }
+}
+
+#[tokio::test]
+async fn test_rename_on_one_instance_is_visible_on_the_other_within_interval() {
+    let p = two_instances().await;
+    p.a.store_wallet("alpha", &[7u8; 64], false).await.unwrap();
+    assert_eq!(names(&p.b.cached_wallet_list().await.unwrap()), vec!["alpha"]);
+    assert!(p.b.cached_wallet("alpha").await.unwrap().is_some());
+
+    p.a.rename_wallet("alpha", "beta").await.unwrap();
+    // 检查间隔内仍是缓存内容
+    assert_eq!(names(&p.b.cached_wallet_list().await.unwrap()), vec!["alpha"]);
+
+    p.clock.advance(chrono::Duration::seconds(1));
+    assert_eq!(names(&p.b.cached_wallet_list().await.unwrap()), vec!["beta"]);
+    assert!(p.b.cached_wallet("alpha").await.unwrap().is_none());
+    assert_eq!(p.b.cached_wallet("beta").await.unwrap().unwrap().name, "beta");
+    assert_eq!(p.b.wallet_cache_stats().flushes, 1);
+
+    // 本实例自己的改动立即可见，不算作外部失效
+    p.a.delete_wallet("beta").await.unwrap();
+    assert!(p.a.cached_wallet_list().await.unwrap().is_empty());
+    p.clock.advance(chrono::Duration::seconds(1));
+    assert!(p.a.cached_wallet_list().await.unwrap().is_empty());
+    assert_eq!(p.a.wallet_cache_stats().flushes, 0);
+}
+
+#[tokio::test]
+async fn test_no_flush_when_nothing_changed() {
+    let p = two_instances().await;
+    p.a.store_wallet("alpha", &[7u8; 64], false).await.unwrap();
+
+    for _ in 0..5 {
+        assert_eq!(names(&p.b.cached_wallet_list().await.unwrap()), vec!["alpha"]);
+        p.clock.advance(chrono::Duration::seconds(2));
+    }
+    let stats = p.b.wallet_cache_stats();
+    assert_eq!(stats.flushes, 0);
+    assert_eq!(stats.misses, 1);
+    assert_eq!(stats.hits, 4);
+    assert_eq!(stats.epoch_read_failures, 0);
+}
+
+#[tokio::test]
+async fn test_epoch_read_failure_degrades_to_ttl() {
+    let p = two_instances().await;
+    p.a.store_wallet("alpha", &[7u8; 64], false).await.unwrap();
+    assert_eq!(names(&p.b.cached_wallet_list().await.unwrap()), vec!["alpha"]);
+
+    // 绕过 WalletStorage 改名并删掉 epoch 表：只剩 TTL 兜底
+    let raw = sqlx::SqlitePool::connect(&p.url).await.unwrap();
+    sqlx::query("UPDATE wallets SET name = 'beta' WHERE name = 'alpha'").execute(&raw).await.unwrap();
+    sqlx::query("DROP TABLE cache_epochs").execute(&raw).await.unwrap();
+
+    p.clock.advance(chrono::Duration::seconds(5));
+    assert_eq!(names(&p.b.cached_wallet_list().await.unwrap()), vec!["alpha"], "stale entry is still served");
+    assert_eq!(p.b.wallet_cache_stats().epoch_read_failures, 1);
+
+    p.clock.advance(chrono::Duration::seconds(30));
+    assert_eq!(names(&p.b.cached_wallet_list().await.unwrap()), vec!["beta"]);
+    assert_eq!(p.b.wallet_cache_stats().epoch_read_failures, 2);
+}
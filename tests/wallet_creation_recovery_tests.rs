@@ -0,0 +1,171 @@
+//! 创建wallet途中崩溃：在每个写入步骤注入存储故障，validate重启后的对账结果
+
+use defi_hot_wallet::core::wallet_info::{SecureWalletData, WalletInfo};
+use defi_hot_wallet::storage::{CreationFault, CreationStep, NetworkInit, WalletStorage};
+
+const NAME: &str = "ghost";
+
+fn init_env() {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+}
+
+/// 结构完整的wallet blob（密封主密钥 32 字节 + GCM tag）
+fn valid_blob(name: &str) -> Vec<u8> {
+    let mut data = SecureWalletData::new(WalletInfo::new(name, false));
+    data.encrypted_master_key = vec![7u8; 48];
+    data.salt = vec![1u8; 32];
+    data.nonce = vec![2u8; 12];
+    bincode::serialize(&data).unwrap()
+}
+
+fn eth_init() -> NetworkInit {
+    NetworkInit {
+        network: "eth".to_string(),
+        address: "0x000000000000000000000000000000000000dead".to_string(),
+        nonce: Some(0),
+        scan_from_block: Some(100),
+        error: None,
+    }
+}
+
+struct Db {
+    url: String,
+    dir: tempfile::TempDir,
+}
+
+impl Db {
+    fn new() -> Self {
+        init_env();
+        let dir = tempfile::tempdir().unwrap();
+        Self { url: format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display()), dir }
+    }
+
+    /// 每次重新打开数据库文件，相当于进程重启
+    async fn open(&self) -> WalletStorage {
+        WalletStorage::new_with_url(&self.url).await.unwrap()
+    }
+
+    async fn open_with_fault(&self, fault: CreationFault) -> WalletStorage {
+        self.open().await.with_creation_fault(fault)
+    }
+}
+
+async fn assert_invisible(storage: &WalletStorage) {
+    assert!(storage.load_wallet(NAME).await.is_err());
+    assert!(storage.list_wallets().await.unwrap().iter().all(|w| w.name != NAME));
+    assert!(storage.list_wallets_page(None, 10).await.unwrap().wallets.iter().all(|w| w.name != NAME));
+    assert!(storage.wallet_networks(NAME).await.unwrap().is_empty());
+}
+
+const STEPS: [CreationStep; 4] =
+    [CreationStep::WalletRow, CreationStep::Networks, CreationStep::Journal, CreationStep::Audit];
+
+#[tokio::test]
+async fn test_failed_statement_at_any_step_leaves_nothing() {
+    for step in STEPS {
+        let db = Db::new();
+        let storage = db.open_with_fault(CreationFault::Error(step)).await;
+        let blob = valid_blob(NAME);
+        assert!(storage.create_wallet_with_networks(NAME, &blob, false, &[eth_init()]).await.is_err());
+
+        let storage = db.open().await;
+        assert_invisible(&storage).await;
+        let report = storage.reconcile_pending_wallets(chrono::Duration::zero()).await.unwrap();
+        assert_eq!(report.checked, 0, "{:?}", step);
+    }
+}
+
+#[tokio::test]
+async fn test_crash_before_audit_is_discarded_on_restart() {
+    for step in [CreationStep::WalletRow, CreationStep::Networks, CreationStep::Journal] {
+        let db = Db::new();
+        let storage = db.open_with_fault(CreationFault::Crash(step)).await;
+        let blob = valid_blob(NAME);
+        assert!(storage.create_wallet_with_networks(NAME, &blob, false, &[eth_init()]).await.is_err());
+
+        let storage = db.open().await;
+        assert_invisible(&storage).await;
+        let report = storage.reconcile_pending_wallets(chrono::Duration::zero()).await.unwrap();
+        assert_eq!((report.checked, report.finalized, report.discarded), (1, 0, 1), "{:?}", step);
+        assert_eq!(report.decisions[0].action, "discarded");
+        assert!(report.decisions[0].reason.contains("missing"), "{}", report.decisions[0].reason);
+
+        // 幽灵行及其network行都已删除，决定写入审计
+        assert_invisible(&storage).await;
+        let audit = storage.get_audit_logs(Some(&report.decisions[0].wallet_id)).await.unwrap();
+        assert!(audit.iter().any(|log| log.action == "wallet_creation_discarded"));
+        let again = storage.reconcile_pending_wallets(chrono::Duration::zero()).await.unwrap();
+        assert_eq!(again.checked, 0);
+    }
+}
+
+#[tokio::test]
+async fn test_crash_after_all_rows_is_finalized_on_restart() {
+    let db = Db::new();
+    let storage = db.open_with_fault(CreationFault::Crash(CreationStep::Audit)).await;
+    let blob = valid_blob(NAME);
+    assert!(storage.create_wallet_with_networks(NAME, &blob, false, &[eth_init()]).await.is_err());
+
+    let storage = db.open().await;
+    assert_invisible(&storage).await;
+    // 还没到宽限期的不处理
+    let early = storage.reconcile_pending_wallets(chrono::Duration::hours(1)).await.unwrap();
+    assert_eq!(early.checked, 0);
+
+    let report = storage.reconcile_pending_wallets(chrono::Duration::zero()).await.unwrap();
+    assert_eq!((report.checked, report.finalized, report.discarded), (1, 1, 0));
+    let (loaded, _) = storage.load_wallet(NAME).await.unwrap();
+    assert_eq!(loaded, blob);
+    assert_eq!(storage.wallet_networks(NAME).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_unusable_blob_is_discarded_even_with_all_rows() {
+    let db = Db::new();
+    let storage = db.open_with_fault(CreationFault::Crash(CreationStep::Audit)).await;
+    assert!(storage.create_wallet_with_networks(NAME, b"not a wallet", false, &[eth_init()]).await.is_err());
+
+    let storage = db.open().await;
+    let report = storage.reconcile_pending_wallets(chrono::Duration::zero()).await.unwrap();
+    assert_eq!((report.finalized, report.discarded), (0, 1));
+    assert!(report.decisions[0].reason.contains("blob does not decode"), "{}", report.decisions[0].reason);
+    assert_invisible(&storage).await;
+}
+
+#[tokio::test]
+async fn test_recreating_a_name_held_by_a_ghost_succeeds() {
+    let db = Db::new();
+    let storage = db.open_with_fault(CreationFault::Crash(CreationStep::Networks)).await;
+    let ghost_blob = valid_blob(NAME);
+    assert!(storage.create_wallet_with_networks(NAME, &ghost_blob, false, &[eth_init()]).await.is_err());
+
+    // 不经对账直接重建：幽灵先被清理，名字可以复用
+    let storage = db.open().await;
+    let mut blob = valid_blob(NAME);
+    blob.push(0);
+    storage.create_wallet_with_networks(NAME, &blob, true, &[eth_init()]).await.unwrap();
+    let (loaded, quantum_safe) = storage.load_wallet(NAME).await.unwrap();
+    assert_eq!(loaded, blob);
+    assert!(quantum_safe);
+    assert_eq!(storage.list_wallets().await.unwrap().len(), 1);
+    assert_eq!(storage.reconcile_pending_wallets(chrono::Duration::zero()).await.unwrap().checked, 0);
+
+    // 普通创建同样会清理幽灵
+    let storage = db.open_with_fault(CreationFault::Crash(CreationStep::Journal)).await;
+    assert!(storage.store_wallet("other", &valid_blob("other"), false).await.is_err());
+    db.open().await.store_wallet("other", &valid_blob("other"), false).await.unwrap();
+    assert_eq!(db.open().await.list_wallets().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_schema_init_lock_file_is_removed_and_stale_one_ignored() {
+    let db = Db::new();
+    let lock_path = db.dir.path().join("wallet.db-init.lock");
+    // 崩溃的进程留下的锁文件不再被锁定，不阻塞启动
+    std::fs::write(&lock_path, b"").unwrap();
+
+    let (first, second) = tokio::join!(db.open(), db.open());
+    assert!(first.list_wallets().await.unwrap().is_empty());
+    assert!(second.list_wallets().await.unwrap().is_empty());
+    assert!(!lock_path.exists());
+}
@@ -0,0 +1,247 @@
+//! 多network一次性创建wallet：全部成功、RPC 不可用时降级为 needs_sync、
+//! 存储failed时不留任何残留行，以及 API 的 `initialize_networks` / 补全接口
+
+mod util;
+
+use axum_test::TestServer;
+use ethers::providers::{MockProvider, Provider};
+use ethers::types::{U256, U64};
+use serde_json::{json, Value};
+use sqlx::sqlite::SqlitePool;
+use std::sync::Arc;
+
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::core::wallet_manager::{CreateWalletOptions, WalletManager};
+use defi_hot_wallet::storage::{WalletStorage, NETWORK_NEEDS_SYNC, NETWORK_READY};
+
+const PASSWORD: &str = "Full!Create#Passw0rd";
+const API_KEY: &str = "wallet-full-creation-admin-key";
+const SESSION: &str = "wallet-full-creation-session";
+const ADDRESS: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+
+struct Harness {
+    manager: WalletManager,
+    storage: WalletStorage,
+    pool: SqlitePool,
+    _dir: tempfile::TempDir,
+}
+
+async fn harness() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let config = util::file_config(dir.path());
+    let url = config.storage.database_url.clone();
+    let manager = WalletManager::new(&config).await.unwrap();
+    let storage = WalletStorage::new_with_url(&url).await.unwrap();
+    let pool = SqlitePool::connect(&url).await.unwrap();
+    Harness { manager, storage, pool, _dir: dir }
+}
+
+fn mocked(network: &str, chain_id: u64) -> (Arc<EthereumClient<MockProvider>>, MockProvider) {
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    (Arc::new(EthereumClient::new_with_provider_and_chain(provider, network, chain_id)), mock)
+}
+
+/// MockProvider 后进先出：先 nonce，后区块高度
+fn answer(mock: &MockProvider, nonce: u64, height: u64) {
+    mock.push(U64::from(height)).unwrap();
+    mock.push(U256::from(nonce)).unwrap();
+}
+
+fn options(networks: &[&str]) -> CreateWalletOptions {
+    CreateWalletOptions {
+        password: PASSWORD.to_string(),
+        quantum_safe: false,
+        networks: networks.iter().map(|n| n.to_string()).collect(),
+        derivation_path: None,
+        passphrase: None,
+    }
+}
+
+async fn count(pool: &SqlitePool, sql: &str) -> i64 {
+    sqlx::query_scalar(sql).fetch_one(pool).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_full_success_registers_every_network() {
+    let h = harness().await;
+    let (eth, eth_mock) = mocked("eth", 1);
+    let (polygon, polygon_mock) = mocked("polygon", 137);
+    answer(&eth_mock, 7, 19_000_000);
+    answer(&polygon_mock, 0, 55_000_000);
+    let clients = ClientRegistry::new().with_client("eth", eth).with_client("polygon", polygon);
+
+    let statuses = h
+        .manager
+        .create_wallet_full("treasury", options(&["eth", "polygon", "eth"]), &h.storage, &clients)
+        .await
+        .unwrap();
+
+    assert_eq!(statuses.len(), 2, "duplicate networks are ignored");
+    assert!(statuses.iter().all(|s| s.status == NETWORK_READY && s.error.is_none()), "{:?}", statuses);
+    assert_eq!(statuses[0].nonce, Some(7));
+    // 同一主密钥在所有 EVM network上是同一个address
+    assert_eq!(statuses[0].address, statuses[1].address);
+
+    let rows = h.storage.wallet_networks("treasury").await.unwrap();
+    assert_eq!(rows.iter().map(|r| r.network.as_str()).collect::<Vec<_>>(), vec!["eth", "polygon"]);
+    assert_eq!(rows[0].initial_nonce, Some(7));
+    let cursor = h.storage.deposit_scan_cursor("treasury", "polygon").await.unwrap().unwrap();
+    assert_eq!(cursor.next_block, Some(55_000_000));
+    let seeded: i64 = sqlx::query_scalar("SELECT next_nonce FROM nonces WHERE network = 'eth' AND address = ?1")
+        .bind(&statuses[0].address)
+        .fetch_one(&h.pool)
+        .await
+        .unwrap();
+    assert_eq!(seeded, 7);
+
+    assert!(h.storage.load_wallet("treasury").await.is_ok());
+    let wallets = h.manager.list_wallets().await.unwrap();
+    assert_eq!(wallets[0].networks, vec!["eth".to_string(), "polygon".to_string()]);
+
+    let err = h
+        .manager
+        .create_wallet_full("treasury", options(&["eth"]), &h.storage, &clients)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, WalletError::ValidationError(_)), "{}", err);
+}
+
+#[tokio::test]
+async fn test_rpc_down_degrades_to_needs_sync_and_completes_later() {
+    let h = harness().await;
+    // eth 有客户端但没有应答（RPC 错误）；polygon 根本没有客户端
+    let (eth, eth_mock) = mocked("eth", 1);
+    let clients = ClientRegistry::new().with_client("eth", eth);
+
+    let statuses = h
+        .manager
+        .create_wallet_full("cold", options(&["eth", "polygon"]), &h.storage, &clients)
+        .await
+        .unwrap();
+    for status in &statuses {
+        assert_eq!(status.status, NETWORK_NEEDS_SYNC, "{:?}", status);
+        assert!(status.nonce.is_none());
+        assert!(status.error.is_some());
+    }
+    let rows = h.storage.wallet_networks("cold").await.unwrap();
+    assert!(rows.iter().all(|r| r.status == NETWORK_NEEDS_SYNC && r.last_error.is_some()));
+    assert_eq!(h.storage.deposit_scan_cursor("cold", "eth").await.unwrap().unwrap().next_block, None);
+    assert_eq!(count(&h.pool, "SELECT COUNT(*) FROM nonces").await, 0);
+
+    // RPC 恢复后补全
+    answer(&eth_mock, 3, 19_000_100);
+    let status = h.manager.initialize_network("cold", "eth", PASSWORD, &h.storage, &clients).await.unwrap();
+    assert_eq!(status.status, NETWORK_READY);
+    assert_eq!(status.address, statuses[0].address);
+    assert_eq!(status.nonce, Some(3));
+    let eth_row = h.storage.wallet_networks("cold").await.unwrap().into_iter().find(|r| r.network == "eth").unwrap();
+    assert_eq!(eth_row.status, NETWORK_READY);
+    assert_eq!(eth_row.last_error, None);
+    assert_eq!(h.storage.deposit_scan_cursor("cold", "eth").await.unwrap().unwrap().next_block, Some(19_000_100));
+    assert_eq!(count(&h.pool, "SELECT COUNT(*) FROM nonces").await, 1);
+}
+
+#[tokio::test]
+async fn test_injected_storage_failure_leaves_no_rows() {
+    let h = harness().await;
+    let (eth, eth_mock) = mocked("eth", 1);
+    answer(&eth_mock, 1, 100);
+    let clients = ClientRegistry::new().with_client("eth", eth);
+
+    // 最后写入的表failed：前面的 wallets / wallet_networks / nonces 行必须一起回滚
+    sqlx::query(
+        "CREATE TRIGGER fail_scan_cursor BEFORE INSERT ON deposit_scan_cursors \
+         BEGIN SELECT RAISE(ABORT, 'injected failure'); END",
+    )
+    .execute(&h.pool)
+    .await
+    .unwrap();
+
+    let err = h
+        .manager
+        .create_wallet_full("doomed", options(&["eth"]), &h.storage, &clients)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, WalletError::StorageError(_)), "{}", err);
+    assert!(err.to_string().contains("injected failure"), "{}", err);
+
+    for sql in [
+        "SELECT COUNT(*) FROM wallets",
+        "SELECT COUNT(*) FROM wallet_networks",
+        "SELECT COUNT(*) FROM deposit_scan_cursors",
+        "SELECT COUNT(*) FROM nonces",
+        "SELECT COUNT(*) FROM events_journal",
+        "SELECT COUNT(*) FROM audit_logs",
+    ] {
+        assert_eq!(count(&h.pool, sql).await, 0, "{}", sql);
+    }
+    assert!(h.manager.list_wallets().await.unwrap().is_empty());
+
+    // 没有残留，同名重试可以成功
+    sqlx::query("DROP TRIGGER fail_scan_cursor").execute(&h.pool).await.unwrap();
+    answer(&eth_mock, 1, 100);
+    let statuses = h.manager.create_wallet_full("doomed", options(&["eth"]), &h.storage, &clients).await.unwrap();
+    assert_eq!(statuses[0].status, NETWORK_READY);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_api_create_with_initialize_networks_and_complete_later() {
+    let dir = tempfile::tempdir().unwrap();
+    let (eth, eth_mock) = mocked("eth", 1);
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY))
+        .await
+        .with_chain_clients(ClientRegistry::new().with_client("eth", eth));
+    util::sign_in(&server, "init@example.com", SESSION).await;
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    // 非 EVM network不能登记非托管 EVM address，请求被拒绝且不创建wallet
+    app.post("/api/wallets")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "name": "hot", "wallet_address": ADDRESS, "initialize_networks": ["btc"] }))
+        .await
+        .assert_status_bad_request();
+
+    // eth 没有应答 → needs_sync；polygon 没有客户端 → needs_sync
+    let res = app
+        .post("/api/wallets")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "name": "hot", "wallet_address": ADDRESS, "initialize_networks": ["ethereum", "polygon"] }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    let networks = body["networks"].as_array().unwrap();
+    assert_eq!(networks.len(), 2);
+    assert_eq!(networks[0]["network"], "eth");
+    assert_eq!(networks[0]["status"], "needs_sync");
+    assert_eq!(networks[0]["address"], ADDRESS);
+    assert!(networks[0]["error"].is_string());
+    assert_eq!(storage.wallet_networks("hot").await.unwrap().len(), 2);
+
+    answer(&eth_mock, 12, 19_000_000);
+    let res = app
+        .post("/api/wallets/hot/networks/eth/initialize")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body, json!({ "network": "eth", "address": ADDRESS, "status": "ready", "nonce": 12 }));
+
+    app.post("/api/wallets/missing/networks/eth/initialize")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await
+        .assert_status_not_found();
+    app.post("/api/wallets/hot/networks/eth/initialize").await.assert_status_unauthorized();
+
+    // 不带 initialize_networks 的创建响应保持原样
+    let body: Value = app
+        .post("/api/wallets")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "name": "plain", "wallet_address": "0x000000000000000000000000000000000000dEaD" }))
+        .await
+        .json();
+    assert!(body.get("networks").is_none());
+}
@@ -0,0 +1,310 @@
+//! wallet组：成员 CRUD、跨wallet合并历史的排序与分页、组清空 dry run 报告，
+//! 以及非 owner 的访问控制
+
+mod util;
+
+use async_trait::async_trait;
+use axum_test::TestServer;
+use chrono::{TimeZone, Utc};
+use ethers::types::U256;
+use ethers::utils::parse_ether;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::gas_oracle::GasOracle;
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::storage::TransactionRecord;
+
+const API_KEY: &str = "groups-test-admin-key";
+const OWNER: &str = "groups-owner-session";
+const OTHER: &str = "groups-other-session";
+const GROUP: &str = "ops-eu";
+
+const ADDR_A: &str = "0x1111111111111111111111111111111111111111";
+const ADDR_B: &str = "0x2222222222222222222222222222222222222222";
+const ADDR_C: &str = "0x3333333333333333333333333333333333333333";
+const ADDR_OTHER: &str = "0x4444444444444444444444444444444444444444";
+
+/// 固定 gas price 与按address的balance，与成员的并发query顺序无关
+struct FixedOracle {
+    gas_price: U256,
+    balances: HashMap<String, U256>,
+}
+
+#[async_trait]
+impl GasOracle for FixedOracle {
+    async fn gas_price(&self, _network: &str) -> Result<U256, WalletError> {
+        Ok(self.gas_price)
+    }
+
+    async fn native_balance(&self, _network: &str, address: &str) -> Result<U256, WalletError> {
+        Ok(self.balances.get(&address.to_ascii_lowercase()).copied().unwrap_or_default())
+    }
+
+    fn supports(&self, network: &str) -> bool {
+        network == "eth"
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    server: WalletServer,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let oracle = FixedOracle {
+        gas_price: U256::from(1_000_000_000u64),
+        balances: HashMap::from([
+            (ADDR_A.to_string(), parse_ether("1").unwrap()),
+            // 够 gas price × 21000，但不够发送路径的 EIP-1559 费用上限（42000 gwei）
+            (ADDR_B.to_string(), parse_ether("0.00003").unwrap()),
+            (ADDR_C.to_string(), parse_ether("2").unwrap()),
+        ]),
+    };
+    let server =
+        util::test_server(dir.path(), util::memory_config(), Some(API_KEY)).await.with_gas_oracle(Arc::new(oracle));
+
+    for (email, token, wallets) in [
+        ("groups-owner@example.com", OWNER, vec![("eu-a", ADDR_A), ("eu-b", ADDR_B), ("eu-c", ADDR_C)]),
+        ("groups-other@example.com", OTHER, vec![("other-wallet", ADDR_OTHER)]),
+    ] {
+        let user_id = util::sign_in(&server, email, token).await;
+        for (name, address) in wallets {
+            server.user_db.link_wallet(&user_id, name, address, None).await.unwrap();
+        }
+    }
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { app, server, _dir: dir }
+}
+
+fn bearer(token: &str) -> String {
+    format!("Bearer {}", token)
+}
+
+impl Harness {
+    async fn create_group(&self) {
+        let res = self.app.post("/api/groups").add_header("Authorization", bearer(OWNER)).json(&json!({ "name": GROUP })).await;
+        res.assert_status_ok();
+        for wallet in ["eu-a", "eu-b", "eu-c"] {
+            self.app
+                .put(&format!("/api/groups/{}/members/{}", GROUP, wallet))
+                .add_header("Authorization", bearer(OWNER))
+                .await
+                .assert_status_ok();
+        }
+    }
+
+    async fn members(&self) -> Vec<String> {
+        let res = self.app.get(&format!("/api/groups/{}", GROUP)).add_header("Authorization", bearer(OWNER)).await;
+        res.assert_status_ok();
+        let body: Value = res.json();
+        body["members"].as_array().unwrap().iter().map(|m| m["wallet_name"].as_str().unwrap().to_string()).collect()
+    }
+
+    /// wallet只在 user_db 中关联过时，先在存储中建出来
+    async fn store_tx(&self, id: &str, wallet: &str, created_at: i64) {
+        let storage = &self.server.storage;
+        if storage.wallet_id(wallet).await.unwrap().is_none() {
+            storage.store_wallet(wallet, b"blob", false).await.unwrap();
+        }
+        let wallet_id = storage.wallet_id(wallet).await.unwrap().unwrap();
+        storage
+            .store_transaction(&TransactionRecord {
+                id: id.to_string(),
+                wallet_id,
+                tx_hash: format!("0x{:064x}", created_at),
+                network: "eth".to_string(),
+                from_address: ADDR_A.to_string(),
+                to_address: ADDR_OTHER.to_string(),
+                amount: "0.1".to_string(),
+                fee: "0.000021".to_string(),
+                status: "confirmed".to_string(),
+                created_at: Utc.timestamp_opt(created_at, 0).unwrap(),
+                confirmed_at: None,
+                integrity_hash: String::new(),
+                fee_actual: None,
+                block_number: None,
+            })
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_group_membership_crud() {
+    let h = build().await;
+    h.create_group().await;
+    assert_eq!(h.members().await, vec!["eu-a", "eu-b", "eu-c"]);
+
+    // 重名 / 非法组名
+    let res = h.app.post("/api/groups").add_header("Authorization", bearer(OWNER)).json(&json!({ "name": GROUP })).await;
+    res.assert_status(axum::http::StatusCode::CONFLICT);
+    assert_eq!(res.json::<Value>()["error"]["code"], "GROUP_EXISTS");
+    let res = h.app.post("/api/groups").add_header("Authorization", bearer(OWNER)).json(&json!({ "name": "bad name!" })).await;
+    res.assert_status_bad_request();
+
+    // 重复添加幂等
+    h.app
+        .put(&format!("/api/groups/{}/members/eu-a", GROUP))
+        .add_header("Authorization", bearer(OWNER))
+        .await
+        .assert_status_ok();
+    assert_eq!(h.members().await, vec!["eu-a", "eu-b", "eu-c"]);
+
+    // 别人的wallet不能加入
+    let res = h
+        .app
+        .put(&format!("/api/groups/{}/members/other-wallet", GROUP))
+        .add_header("Authorization", bearer(OWNER))
+        .await;
+    res.assert_status_not_found();
+    assert_eq!(res.json::<Value>()["error"]["code"], "WALLET_NOT_FOUND");
+
+    h.app
+        .delete(&format!("/api/groups/{}/members/eu-b", GROUP))
+        .add_header("Authorization", bearer(OWNER))
+        .await
+        .assert_status_ok();
+    let res = h.app.delete(&format!("/api/groups/{}/members/eu-b", GROUP)).add_header("Authorization", bearer(OWNER)).await;
+    res.assert_status_not_found();
+    assert_eq!(res.json::<Value>()["error"]["code"], "MEMBER_NOT_FOUND");
+    assert_eq!(h.members().await, vec!["eu-a", "eu-c"]);
+
+    // 删除wallet同时移出组
+    h.app.delete("/api/wallets/eu-c").add_header("Authorization", bearer(OWNER)).await.assert_status_ok();
+    assert_eq!(h.members().await, vec!["eu-a"]);
+
+    // 删除组不影响wallet
+    h.app.delete(&format!("/api/groups/{}", GROUP)).add_header("Authorization", bearer(OWNER)).await.assert_status_ok();
+    h.app.get(&format!("/api/groups/{}", GROUP)).add_header("Authorization", bearer(OWNER)).await.assert_status_not_found();
+    let owner_id = h.server.session_store.validate_token(OWNER).await.unwrap();
+    assert!(h.server.user_db.find_user_wallet(&owner_id, "eu-a").await.unwrap().is_some());
+    assert!(h.server.storage.wallet_group_members(GROUP).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_group_history_merges_members_newest_first() {
+    let h = build().await;
+    h.create_group().await;
+
+    // 三个wallet时间交错；非成员wallet的记录不出现
+    let base = 1_700_000_000;
+    for (i, wallet) in ["eu-a", "eu-b", "eu-c", "eu-a", "eu-c", "eu-b", "eu-a"].iter().enumerate() {
+        h.store_tx(&format!("group-tx-{}", i), wallet, base + i as i64 * 60).await;
+    }
+    h.store_tx("outsider-tx", "other-wallet", base + 30).await;
+
+    let mut seen = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut req =
+            h.app.get(&format!("/api/groups/{}/history", GROUP)).add_query_param("limit", 2).add_header("Authorization", bearer(OWNER));
+        if let Some(cursor) = &cursor {
+            req = req.add_query_param("cursor", cursor);
+        }
+        let res = req.await;
+        res.assert_status_ok();
+        let body: Value = res.json();
+        let page = body["transactions"].as_array().unwrap();
+        assert!(page.len() <= 2);
+        seen.extend(page.iter().map(|t| (t["wallet"].as_str().unwrap().to_string(), t["hash"].as_str().unwrap().to_string())));
+        match body.get("next_cursor").and_then(Value::as_str) {
+            Some(next) => cursor = Some(next.to_string()),
+            None => break,
+        }
+    }
+
+    let expected: Vec<(String, String)> = ["eu-a", "eu-b", "eu-c", "eu-a", "eu-c", "eu-b", "eu-a"]
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, w)| (w.to_string(), format!("0x{:064x}", base + i as i64 * 60)))
+        .collect();
+    assert_eq!(seen, expected);
+
+    let res = h
+        .app
+        .get(&format!("/api/groups/{}/history", GROUP))
+        .add_query_param("cursor", "not-a-cursor")
+        .add_header("Authorization", bearer(OWNER))
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_CURSOR");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_group_sweep_dry_run_report() {
+    let h = build().await;
+    h.create_group().await;
+
+    let res = h
+        .app
+        .post(&format!("/api/groups/{}/sweep", GROUP))
+        .add_header("Authorization", bearer(OWNER))
+        .json(&json!({ "to": ADDR_C, "network": "eth" }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["dry_run"], true);
+    assert_eq!(body["gas_price_wei"], "1000000000");
+
+    let results: HashMap<&str, &Value> =
+        body["results"].as_array().unwrap().iter().map(|r| (r["wallet"].as_str().unwrap(), r)).collect();
+    let a = results["eu-a"];
+    assert_eq!(a["status"], "would_send");
+    assert_eq!(parse_ether(a["amount"].as_str().unwrap()).unwrap(), parse_ether("0.999958").unwrap());
+    assert_eq!(parse_ether(a["fee"].as_str().unwrap()).unwrap(), parse_ether("0.000042").unwrap());
+    assert_eq!(results["eu-b"]["status"], "skipped");
+    assert_eq!(results["eu-b"]["code"], "INSUFFICIENT_BALANCE");
+    assert_eq!(results["eu-c"]["status"], "skipped");
+    assert_eq!(results["eu-c"]["code"], "SWEEP_DESTINATION");
+    assert_eq!(parse_ether(body["total_amount"].as_str().unwrap()).unwrap(), parse_ether("0.999958").unwrap());
+
+    // dry run 不落任何transaction
+    let (records, _) = h.server.storage.wallet_set_transactions(&["eu-a".to_string()], None, 10).await.unwrap();
+    assert!(records.is_empty());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_non_owner_cannot_read_group() {
+    let h = build().await;
+    h.create_group().await;
+
+    let res = h.app.get(&format!("/api/groups/{}", GROUP)).add_header("Authorization", bearer(OTHER)).await;
+    res.assert_status_forbidden();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "GROUP_ACCESS_DENIED");
+    assert!(body.get("members").is_none());
+    for path in ["balances", "history"] {
+        h.app
+            .get(&format!("/api/groups/{}/{}", GROUP, path))
+            .add_header("Authorization", bearer(OTHER))
+            .await
+            .assert_status_forbidden();
+    }
+    h.app
+        .delete(&format!("/api/groups/{}/members/eu-a", GROUP))
+        .add_header("Authorization", bearer(OTHER))
+        .await
+        .assert_status_forbidden();
+
+    let res = h.app.get("/api/groups").add_header("Authorization", bearer(OTHER)).await;
+    res.assert_status_ok();
+    assert!(res.json::<Value>()["groups"].as_array().unwrap().is_empty());
+
+    h.app.get(&format!("/api/groups/{}", GROUP)).await.assert_status_unauthorized();
+
+    // admin 可以看到所有组
+    let res = h.app.get(&format!("/api/groups/{}", GROUP)).add_header("Authorization", API_KEY).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["members"].as_array().unwrap().len(), 3);
+}
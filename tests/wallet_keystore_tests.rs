@@ -0,0 +1,174 @@
+//! wallet keystore 导出/导入集成测试：两个实例之间迁移wallet，Password错误返回 InvalidPassword
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::core::wallet_info::SecureWalletData;
+use defi_hot_wallet::core::WalletKeyKind;
+use defi_hot_wallet::crypto::wallet_keystore::{WalletKeystore, DEFAULT_ARGON2_M_COST};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::WalletStorageTrait;
+
+const API_KEY: &str = "wallet-keystore-test-api-key-0123456789";
+const PASSWORD: &str = "M1gratedWallet";
+
+async fn build_server(dir: &TempDir) -> WalletServer {
+    let mut config = WalletConfig {
+        storage: StorageConfig {
+            database_url: format!("sqlite://{}?mode=rwc", dir.path().join("wallet.db").display()),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    // 测试中降低导出成本；与 hot_wallet 启动时一样从 [security] 段读取
+    let doc: toml::Value =
+        toml::from_str("[security]\nwallet_keystore_m_cost = 1024\npbkdf2_iterations = 1_000\n").unwrap();
+    config.security = doc["security"].clone().try_into().unwrap();
+    WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap()
+}
+
+#[test]
+fn test_default_export_cost() {
+    assert_eq!(WalletConfig::default().security.wallet_keystore_m_cost, DEFAULT_ARGON2_M_COST);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_wallet_moves_between_instances() {
+    let (source_dir, target_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    let source = build_server(&source_dir).await;
+    source.wallet_manager.create_wallet("treasury", PASSWORD, false).await.unwrap();
+    let address = source.wallet_manager.get_ethereum_address_from_master_key("treasury", PASSWORD).await.unwrap();
+    let source_app = TestServer::new(source.create_router().await).unwrap();
+
+    let res = source_app
+        .post("/api/wallets/treasury/export")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "password": PASSWORD }))
+        .await;
+    res.assert_status_ok();
+    let exported: Value = res.json();
+    assert_eq!(exported["version"], 1);
+    assert_eq!(exported["wallet"]["key_kind"], "hd");
+    assert_eq!(exported["wallet"]["address"], address);
+    assert_eq!(exported["crypto"]["cipher"], "aes-256-gcm");
+    assert_eq!(exported["crypto"]["kdf"], "argon2id");
+    assert_eq!(exported["crypto"]["kdfparams"]["m_cost"], 1024);
+    // 只有加密后的密钥，没有mnemonic
+    assert!(exported.get("mnemonic").is_none() && exported["wallet"].get("mnemonic").is_none());
+
+    let target = build_server(&target_dir).await;
+    let manager = target.wallet_manager.clone();
+    let storage = target.storage.clone();
+    let target_app = TestServer::new(target.create_router().await).unwrap();
+    let res = target_app
+        .post("/api/wallets/import")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "name": "treasury", "keystore": exported, "password": PASSWORD }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["address"], address);
+    assert_eq!(body["wallet_type"], "standard");
+
+    let imported = manager.get_wallet_by_name("treasury").await.unwrap().unwrap();
+    assert_eq!(imported.key_kind, WalletKeyKind::Hd);
+    assert_eq!(manager.get_ethereum_address_from_master_key("treasury", PASSWORD).await.unwrap(), address);
+    // 已经写入数据库
+    let (stored, quantum_safe) = storage.load_wallet("treasury").await.unwrap();
+    let stored: SecureWalletData = bincode::deserialize(&stored).unwrap();
+    assert_eq!(stored.key_kind, WalletKeyKind::Hd);
+    assert_eq!(stored.encrypted_master_key, imported.encrypted_master_key);
+    assert!(!quantum_safe);
+
+    // 同名wallet不能覆盖
+    let res = target_app
+        .post("/api/wallets/import")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "name": "treasury", "keystore": exported.to_string(), "password": PASSWORD }))
+        .await;
+    assert_eq!(res.status_code(), 409);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_wrong_password_is_invalid_password() {
+    let dir = TempDir::new().unwrap();
+    let server = build_server(&dir).await;
+    server.wallet_manager.create_wallet("origin", PASSWORD, false).await.unwrap();
+    let keystore = server.wallet_manager.export_wallet("origin", PASSWORD).await.unwrap();
+
+    let storage: std::sync::Arc<dyn WalletStorageTrait + Send + Sync> = server.storage.clone();
+    let direct = server.wallet_manager.import_wallet("copy", &keystore, "Wr0ngSecret", &storage).await;
+    assert!(matches!(direct, Err(WalletError::InvalidPassword(_))), "{:?}", direct);
+
+    let manager = server.wallet_manager.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    let res = app
+        .post("/api/wallets/import")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "name": "copy", "keystore": keystore, "password": "Wr0ngSecret" }))
+        .await;
+    res.assert_status_unauthorized();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "INVALID_PASSWORD");
+    assert!(manager.get_wallet_by_name("copy").await.unwrap().is_none());
+    assert!(storage.load_wallet("copy").await.is_err());
+
+    // 篡改后的 keystore 同样过不了 MAC 校验
+    let mut tampered: Value = serde_json::from_str(&keystore).unwrap();
+    tampered["wallet"]["multi_sig_threshold"] = json!(2);
+    let res = app
+        .post("/api/wallets/import")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "name": "copy", "keystore": tampered, "password": PASSWORD }))
+        .await;
+    res.assert_status_unauthorized();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_export_requires_api_key_and_wallet_password() {
+    let dir = TempDir::new().unwrap();
+    let server = build_server(&dir).await;
+    server.wallet_manager.create_wallet("guarded", PASSWORD, false).await.unwrap();
+    let manager = server.wallet_manager.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    let res = app.post("/api/wallets/guarded/export").json(&json!({ "password": PASSWORD })).await;
+    res.assert_status_unauthorized();
+
+    let res = app
+        .post("/api/wallets/guarded/export")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "password": "Wr0ngSecret" }))
+        .await;
+    res.assert_status_unauthorized();
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "INVALID_PASSWORD");
+
+    let res = app
+        .post("/api/wallets/missing/export")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "password": PASSWORD }))
+        .await;
+    res.assert_status_not_found();
+
+    // 导出的内容可以独立解析
+    let keystore = WalletKeystore::from_json(&manager.export_wallet("guarded", PASSWORD).await.unwrap()).unwrap();
+    assert_eq!(keystore.wallet.name, "guarded");
+}
@@ -0,0 +1,112 @@
+//! `GET /api/wallets?cursor=&limit=` keyset 分页与流式响应集成测试
+
+mod util;
+
+use axum_test::TestServer;
+use std::collections::HashSet;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::api::types::WalletResponse;
+
+const TOKEN: &str = "wallet-list-pagination-token";
+const WALLETS: usize = 1_200;
+
+async fn build_app() -> (TestServer, WalletServer, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server(dir.path(), util::memory_config(), None).await;
+
+    let user_id = util::sign_in(&server, "pager@example.com", TOKEN).await;
+
+    for i in 0..WALLETS {
+        server
+            .user_db
+            .link_wallet(&user_id, &format!("list_{:05}", i), "0x742d35Cc6634C0532925a3b844Bc454e4438f44e", None)
+            .await
+            .unwrap();
+    }
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    (app, server, dir)
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_cursor_iteration_visits_each_wallet_once() {
+    let (app, _server, _dir) = build_app().await;
+
+    let mut seen = HashSet::new();
+    let mut order = Vec::new();
+    let mut cursor: Option<String> = None;
+    let mut pages = 0;
+    loop {
+        let uri = match &cursor {
+            Some(c) => format!("/api/wallets?limit=500&cursor={}", c),
+            None => "/api/wallets?limit=500".to_string(),
+        };
+        let res = app.get(&uri).add_header("Authorization", format!("Bearer {}", TOKEN)).await;
+        res.assert_status_ok();
+        let page: Vec<serde_json::Value> = res.json();
+        assert!(page.len() <= 500);
+        for w in &page {
+            let name = w["name"].as_str().unwrap().to_string();
+            assert!(seen.insert(name.clone()), "wallet {} returned twice", name);
+            order.push(name);
+        }
+        pages += 1;
+        cursor = res.headers().get("x-next-cursor").map(|v| v.to_str().unwrap().to_string());
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    assert_eq!(pages, 3);
+    assert_eq!(seen.len(), WALLETS);
+    let mut sorted = order.clone();
+    sorted.sort();
+    assert_eq!(order, sorted, "wallets must come back in creation order");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_streamed_body_matches_collected_response() {
+    let (app, server, _dir) = build_app().await;
+
+    let res = app.get("/api/wallets").add_header("Authorization", format!("Bearer {}", TOKEN)).await;
+    res.assert_status_ok();
+    let streamed = res.as_bytes().to_vec();
+
+    let user_id = server.session_store.validate_token(TOKEN).await.unwrap();
+    let (rows, next) = server.user_db.get_user_wallets_page(&user_id, None, 500).await.unwrap();
+    assert!(next.is_some());
+    let collected: Vec<WalletResponse> = rows
+        .into_iter()
+        .map(|w| WalletResponse {
+            id: w.name.clone(),
+            name: w.name,
+            address: w.address.unwrap(),
+            quantum_safe: false,
+            wallet_type: Some(w.wallet_type),
+            mnemonic: None,
+            warning: None,
+            preflight: None,
+            networks: None,
+            description: None,
+            metadata: None,
+        })
+        .collect();
+    let expected = serde_json::to_vec(&collected).unwrap();
+
+    assert_eq!(streamed.len(), expected.len());
+    assert_eq!(streamed, expected);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_invalid_cursor_is_rejected() {
+    let (app, _server, _dir) = build_app().await;
+    let res = app
+        .get("/api/wallets?cursor=bm90LWEtY3Vyc29y")
+        .add_header("Authorization", format!("Bearer {}", TOKEN))
+        .await;
+    res.assert_status_bad_request();
+}
@@ -0,0 +1,313 @@
+//! wallet允许network集合：受限wallet在各类操作上被拒、桥接目标链check、
+//! 不受限wallet不受影响，以及集合变更的审计与历史警告
+
+mod util;
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use chrono::Utc;
+use ethers::signers::Signer;
+use ethers::types::U256;
+use ethers::utils::parse_ether;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::gas_oracle::GasOracle;
+use defi_hot_wallet::core::config::NetworkConfig;
+use defi_hot_wallet::core::errors::WalletError;
+use defi_hot_wallet::storage::TransactionRecord;
+
+const API_KEY: &str = "allowlist-test-admin-key";
+const OWNER: &str = "allowlist-owner-session";
+const OTHER: &str = "allowlist-other-session";
+const PASSWORD: &str = "Allowl1st!Secure#2024";
+const RESTRICTED: &str = "vault";
+const OPEN: &str = "spending";
+const TO: &str = "0x000000000000000000000000000000000000dEaD";
+
+/// 任何network都有 1 gwei gas price 与 1 ETH balance
+struct FixedOracle;
+
+#[async_trait]
+impl GasOracle for FixedOracle {
+    async fn gas_price(&self, _network: &str) -> Result<U256, WalletError> {
+        Ok(U256::from(1_000_000_000u64))
+    }
+
+    async fn native_balance(&self, _network: &str, _address: &str) -> Result<U256, WalletError> {
+        Ok(parse_ether("1").unwrap())
+    }
+
+    fn supports(&self, _network: &str) -> bool {
+        true
+    }
+}
+
+struct Harness {
+    app: TestServer,
+    server: WalletServer,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = util::memory_config();
+    // the bridge only routes between configured networks
+    config.blockchain.networks.insert(
+        "polygon".to_string(),
+        NetworkConfig {
+            name: "polygon".to_string(),
+            rpc_url: "http://127.0.0.1:1".to_string(),
+            chain_id: 137,
+            explorer_url_template: None,
+            rate_limit: None,
+            fallback_endpoints: Vec::new(),
+            pending_expiry_seconds: None,
+        },
+    );
+    let server = util::test_server(dir.path(), config, Some(API_KEY)).await.with_gas_oracle(Arc::new(FixedOracle));
+
+    let mut user_ids = Vec::new();
+    for (email, token) in [("allowlist-owner@example.com", OWNER), ("allowlist-other@example.com", OTHER)] {
+        user_ids.push(util::sign_in(&server, email, token).await);
+    }
+    for wallet in [RESTRICTED, OPEN] {
+        util::create_wallet(&server, wallet, PASSWORD).await;
+        let address = server.wallet_manager.ethereum_signer(wallet, PASSWORD).await.unwrap().address();
+        server.user_db.link_wallet(&user_ids[0], wallet, &format!("{:#x}", address), None).await.unwrap();
+    }
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { app, server, _dir: dir }
+}
+
+fn bearer(token: &str) -> String {
+    format!("Bearer {}", token)
+}
+
+fn assert_not_allowed(res: &TestResponse) {
+    res.assert_status(StatusCode::FORBIDDEN);
+    let body: Value = res.json();
+    assert_eq!(body["error"]["code"], "NETWORK_NOT_ALLOWED", "{}", body);
+}
+
+impl Harness {
+    async fn set_allowed(&self, wallet: &str, allowed: Value) -> Value {
+        let res = self
+            .app
+            .put(&format!("/api/wallets/{}/networks", wallet))
+            .add_header("Authorization", bearer(OWNER))
+            .json(&json!({ "allowed_networks": allowed }))
+            .await;
+        res.assert_status_ok();
+        res.json()
+    }
+
+    async fn balance(&self, wallet: &str, network: &str) -> TestResponse {
+        self.app
+            .get(&format!("/api/wallets/{}/balance?network={}", wallet, network))
+            .add_header("Authorization", bearer(OWNER))
+            .await
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_restricted_wallet_blocked_on_other_networks() {
+    let h = build().await;
+    let body = h.set_allowed(RESTRICTED, json!(["ethereum", "eth"])).await;
+    // 规范化并去重
+    assert_eq!(body["allowed_networks"], json!(["eth"]));
+
+    // balance
+    h.balance(RESTRICTED, "eth").await.assert_status_ok();
+    assert_not_allowed(&h.balance(RESTRICTED, "polygon").await);
+
+    // 发送：在签名和任何 RPC 之前被拒
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/send", RESTRICTED))
+        .add_header("Authorization", bearer(OWNER))
+        .json(&json!({ "to": TO, "amount": "0.1", "network": "polygon", "password": PASSWORD }))
+        .await;
+    assert_not_allowed(&res);
+
+    // 多签
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/send_multi_sig", RESTRICTED))
+        .add_header("Authorization", API_KEY)
+        .json(&json!({ "to": TO, "amount": "0.1", "network": "bsc", "signatures": ["sig1", "sig2"] }))
+        .await;
+    assert_not_allowed(&res);
+
+    // 组归集：该成员失败，报告中带原因
+    let res = h.app.post("/api/groups").add_header("Authorization", bearer(OWNER)).json(&json!({ "name": "ops" })).await;
+    res.assert_status_ok();
+    h.app
+        .put(&format!("/api/groups/ops/members/{}", RESTRICTED))
+        .add_header("Authorization", bearer(OWNER))
+        .await
+        .assert_status_ok();
+    let res = h
+        .app
+        .post("/api/groups/ops/sweep")
+        .add_header("Authorization", bearer(OWNER))
+        .json(&json!({ "to": TO, "network": "polygon", "dry_run": false, "passwords": { RESTRICTED: PASSWORD } }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["results"][0]["status"], "failed", "{}", body);
+    assert_eq!(body["results"][0]["code"], "NETWORK_NOT_ALLOWED");
+
+    // address派生（托管wallet补初始化network）
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/networks/polygon/initialize", RESTRICTED))
+        .add_header("Authorization", API_KEY)
+        .json(&json!({ "password": PASSWORD }))
+        .await;
+    assert_not_allowed(&res);
+
+    // 直接走 WalletManager 的调用同样被拒
+    let manager = &h.server.wallet_manager;
+    let err = manager.get_balance(RESTRICTED, "polygon", PASSWORD).await.unwrap_err();
+    assert!(matches!(err, WalletError::NetworkNotAllowed(ref msg) if msg.contains("[eth]")), "{}", err);
+    let err = manager.send_transaction(RESTRICTED, TO, "0.1", "bsc", PASSWORD).await.unwrap_err();
+    assert!(matches!(err, WalletError::NetworkNotAllowed(_)), "{}", err);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_bridge_destination_must_be_allowed() {
+    let h = build().await;
+    h.set_allowed(RESTRICTED, json!(["eth"])).await;
+
+    // 资金从允许的 eth 离开，但目标链 polygon 不在集合内
+    let res = h
+        .app
+        .post("/api/bridge")
+        .add_header("Authorization", API_KEY)
+        .json(&json!({
+            "from_wallet": RESTRICTED,
+            "from_chain": "eth",
+            "to_chain": "polygon",
+            "token": "USDC",
+            "amount": "1.0"
+        }))
+        .await;
+    assert_not_allowed(&res);
+
+    let manager = &h.server.wallet_manager;
+    for (from, to) in [("eth", "polygon"), ("polygon", "eth")] {
+        let err = manager.bridge_assets(RESTRICTED, from, to, "USDC", "1.0").await.unwrap_err();
+        assert!(matches!(err, WalletError::NetworkNotAllowed(ref msg) if msg.contains("polygon")), "{}", err);
+    }
+
+    h.set_allowed(RESTRICTED, json!(["eth", "polygon"])).await;
+    assert!(manager.bridge_assets(RESTRICTED, "eth", "polygon", "USDC", "1.0").await.is_ok());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_unrestricted_wallets_unaffected() {
+    let h = build().await;
+    h.set_allowed(RESTRICTED, json!(["eth"])).await;
+
+    for network in ["eth", "polygon", "bsc"] {
+        h.balance(OPEN, network).await.assert_status_ok();
+    }
+    assert!(h.server.wallet_manager.bridge_assets(OPEN, "eth", "polygon", "USDC", "1.0").await.is_ok());
+
+    let res = h.app.get(&format!("/api/wallets/{}/networks", OPEN)).add_header("Authorization", bearer(OWNER)).await;
+    res.assert_status_ok();
+    assert_eq!(res.json::<Value>()["allowed_networks"], Value::Null);
+
+    // 列表带上集合；null 即不受限
+    let res = h.app.get("/api/wallets").add_header("Authorization", bearer(OWNER)).await;
+    res.assert_status_ok();
+    let wallets: Vec<Value> = res.json();
+    let allowed = |name: &str| wallets.iter().find(|w| w["name"] == name).unwrap()["allowed_networks"].clone();
+    assert_eq!(allowed(RESTRICTED), json!(["eth"]));
+    assert_eq!(allowed(OPEN), Value::Null);
+
+    // 清除限制后恢复
+    h.set_allowed(RESTRICTED, Value::Null).await;
+    h.balance(RESTRICTED, "polygon").await.assert_status_ok();
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_allowlist_changes_are_audited_and_warn_on_history() {
+    let h = build().await;
+    let wallet_id = h.server.storage.wallet_id(RESTRICTED).await.unwrap().unwrap();
+    h.server
+        .storage
+        .store_transaction(&TransactionRecord {
+            id: "polygon-tx".to_string(),
+            wallet_id,
+            tx_hash: format!("0x{:064x}", 7),
+            network: "polygon".to_string(),
+            from_address: TO.to_string(),
+            to_address: TO.to_string(),
+            amount: "0.1".to_string(),
+            fee: "0.000021".to_string(),
+            status: "confirmed".to_string(),
+            created_at: Utc::now(),
+            confirmed_at: None,
+            integrity_hash: String::new(),
+            fee_actual: None,
+            block_number: None,
+        })
+        .await
+        .unwrap();
+
+    // 只有 owner 或 admin 能改
+    let res = h
+        .app
+        .put(&format!("/api/wallets/{}/networks", RESTRICTED))
+        .add_header("Authorization", bearer(OTHER))
+        .json(&json!({ "allowed_networks": ["eth"] }))
+        .await;
+    assert!(res.status_code().is_client_error(), "{}", res.text());
+
+    let body = h.set_allowed(RESTRICTED, json!(["eth", "polygon"])).await;
+    assert!(body.get("warnings").is_none(), "{}", body);
+    // 移除有历史的 polygon：照常生效，附带警告
+    let body = h.set_allowed(RESTRICTED, json!(["eth"])).await;
+    assert_eq!(body["allowed_networks"], json!(["eth"]));
+    let warnings = body["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].as_str().unwrap().starts_with("polygon has 1 recorded transaction"), "{}", body);
+    assert_not_allowed(&h.balance(RESTRICTED, "polygon").await);
+
+    let res = h
+        .app
+        .put(&format!("/api/wallets/{}/networks", RESTRICTED))
+        .add_header("Authorization", API_KEY)
+        .json(&json!({ "allowed_networks": null }))
+        .await;
+    res.assert_status_ok();
+
+    let mut changes: Vec<Value> = h
+        .server
+        .storage
+        .get_audit_logs(Some(RESTRICTED))
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|log| log.action == "allowed_networks.updated")
+        .map(|log| serde_json::from_str(log.details.as_deref().unwrap()).unwrap())
+        .collect();
+    changes.sort_by_key(|c| c["previous"].as_array().map_or(usize::MAX, |p| p.len()));
+    assert_eq!(changes.len(), 3);
+    assert_eq!(changes[0]["previous"], json!(["eth"]));
+    assert_eq!(changes[0]["allowed_networks"], Value::Null);
+    assert_eq!(changes[0]["updated_by"], "admin");
+    assert_eq!(changes[1]["previous"], json!(["eth", "polygon"]));
+    assert_eq!(changes[1]["allowed_networks"], json!(["eth"]));
+    assert_eq!(changes[2]["previous"], Value::Null);
+    assert_ne!(changes[2]["updated_by"], "admin");
+}
@@ -0,0 +1,234 @@
+//! wallet描述与元数据：merge patch（含删除键）、大小与键数上限、疑似密钥拒绝、
+//! 列表搜索（名称/描述/元数据值，含 `%` `_` 等特殊字符），以及备份恢复往返
+
+mod util;
+
+use axum_test::TestServer;
+use base64::Engine;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use defi_hot_wallet::storage::WalletStorage;
+
+const API_KEY: &str = "wallet-notes-admin-key-0123456789";
+const OWNER_TOKEN: &str = "wallet-notes-owner-token";
+const WALLETS: [&str; 4] = ["treasury", "payroll-eu", "cold_store", "hot1"];
+const MNEMONIC: &str = "abandon ability able about above absent absorb abstract absurd abuse access accident";
+
+struct Harness {
+    app: TestServer,
+    storage: Arc<WalletStorage>,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    std::env::set_var("TEST_SKIP_DECRYPT", "1");
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY)).await;
+
+    let user_id = util::sign_in(&server, "notes-owner@example.com", OWNER_TOKEN).await;
+    for name in WALLETS {
+        server.wallet_manager.create_wallet(name, "N0tes!Wallet#2024", false).await.unwrap();
+        server.storage.store_wallet(name, b"sealed", false).await.unwrap();
+        server.user_db.link_wallet(&user_id, name, "0x742d35Cc6634C0532925a3b844Bc454e4438f44e", None).await.unwrap();
+    }
+
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+    Harness { app, storage, _dir: dir }
+}
+
+impl Harness {
+    async fn patch(&self, wallet: &str, body: Value) -> axum_test::TestResponse {
+        self.app.patch(&format!("/api/wallets/{}", wallet)).add_header("X-API-KEY", API_KEY).json(&body).await
+    }
+
+    async fn patch_ok(&self, wallet: &str, body: Value) -> Value {
+        let res = self.patch(wallet, body).await;
+        res.assert_status_ok();
+        res.json()
+    }
+
+    async fn search(&self, query: &str) -> Vec<String> {
+        let res = self
+            .app
+            .get("/api/wallets")
+            .add_query_param("search", query)
+            .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+            .await;
+        res.assert_status_ok();
+        res.json::<Vec<Value>>().iter().map(|w| w["name"].as_str().unwrap().to_string()).collect()
+    }
+}
+
+fn code(res: &axum_test::TestResponse) -> String {
+    res.json::<Value>()["error"]["code"].as_str().unwrap_or_default().to_string()
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_merge_patch_updates_and_deletes_keys() {
+    let h = build().await;
+    let body = h.patch_ok("treasury", json!({ "description": "main float", "metadata": { "a": 1, "b": "x" } })).await;
+    assert_eq!(body["description"], "main float");
+    assert_eq!(body["metadata"], json!({ "a": 1, "b": "x" }));
+
+    // null 删除键，未出现的键与描述保持不变；删除不存在的键不报错
+    let body = h.patch_ok("treasury", json!({ "metadata": { "a": null, "c": true, "missing": null } })).await;
+    assert_eq!(body["description"], "main float");
+    assert_eq!(body["metadata"], json!({ "b": "x", "c": true }));
+
+    let body = h.patch_ok("treasury", json!({ "description": null })).await;
+    assert!(body["description"].is_null());
+    assert_eq!(body["metadata"], json!({ "b": "x", "c": true }));
+
+    let body = h.patch_ok("treasury", json!({ "metadata": { "b": null, "c": null } })).await;
+    assert_eq!(body["metadata"], json!({}));
+    assert!(h.storage.wallet_notes("treasury").await.unwrap().unwrap().is_empty());
+
+    let res = h.patch("ghost", json!({ "description": "x" })).await;
+    res.assert_status_not_found();
+    h.app.patch("/api/wallets/treasury").json(&json!({ "description": "x" })).await.assert_status_unauthorized();
+
+    let logs = h.storage.get_audit_logs(Some("treasury")).await.unwrap();
+    assert_eq!(logs.iter().filter(|l| l.action == "wallet.notes_updated").count(), 4);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_size_and_key_caps_are_enforced() {
+    let h = build().await;
+    for (body, expected) in [
+        (json!({ "metadata": { "Bad-Key": 1 } }), "INVALID_WALLET_NOTES"),
+        (json!({ "metadata": { "a".repeat(65): 1 } }), "INVALID_WALLET_NOTES"),
+        (json!({ "metadata": { "nested": { "a": 1 } } }), "INVALID_WALLET_NOTES"),
+        (json!({ "description": "d".repeat(1025) }), "WALLET_NOTES_TOO_LARGE"),
+        (json!({ "metadata": { "blob": "v".repeat(4100) } }), "WALLET_NOTES_TOO_LARGE"),
+        (json!({ "metadata": { "hex": format!("0x{}", "ab".repeat(32)) } }), "SECRET_IN_WALLET_NOTES"),
+        (json!({ "metadata": { "api_secret": "hunter2" } }), "SECRET_IN_WALLET_NOTES"),
+        (json!({ "description": format!("seed: {}", MNEMONIC) }), "SECRET_IN_WALLET_NOTES"),
+    ] {
+        let res = h.patch("treasury", body.clone()).await;
+        res.assert_status_bad_request();
+        assert_eq!(code(&res), expected, "{}", body);
+    }
+    h.patch_ok("treasury", json!({ "description": "d".repeat(1024), "metadata": { "a".repeat(64): 1 } })).await;
+
+    // 上限针对合并后的结果：每次都不超限，累计超限的那次被拒绝且不落库
+    let keys = |prefix: &str| -> Value { (0..20).map(|i| (format!("{}{}", prefix, i), json!(i))).collect() };
+    h.patch_ok("payroll-eu", json!({ "metadata": keys("k") })).await;
+    let res = h.patch("payroll-eu", json!({ "metadata": keys("j") })).await;
+    res.assert_status_bad_request();
+    assert_eq!(code(&res), "TOO_MANY_METADATA_KEYS");
+    assert_eq!(h.storage.wallet_notes("payroll-eu").await.unwrap().unwrap().metadata.len(), 20);
+
+    h.patch_ok("hot1", json!({ "metadata": { "first": "v".repeat(3000) } })).await;
+    let res = h.patch("hot1", json!({ "metadata": { "second": "v".repeat(3000) } })).await;
+    assert_eq!(code(&res), "WALLET_NOTES_TOO_LARGE");
+    // 删除后再写入即可
+    h.patch_ok("hot1", json!({ "metadata": { "first": null, "second": "v".repeat(3000) } })).await;
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_search_matches_name_description_and_metadata() {
+    let h = build().await;
+    h.patch_ok("treasury", json!({ "description": "Main treasury, 50% of float" })).await;
+    h.patch_ok("payroll-eu", json!({ "metadata": { "team": "Payroll", "cost.center": 4410 } })).await;
+    h.patch_ok("hot1", json!({ "description": "daily ops", "metadata": { "region": "us_east", "active": true } }))
+        .await;
+
+    assert_eq!(h.search("FLOAT").await, ["treasury"]);
+    assert_eq!(h.search("payroll").await, ["payroll-eu"]);
+    assert_eq!(h.search("4410").await, ["payroll-eu"]);
+    assert_eq!(h.search("US_EAST").await, ["hot1"]);
+    assert_eq!(h.search("Ops").await, ["hot1"]);
+    // `%` `_` 按字面匹配，而不是通配符
+    assert_eq!(h.search("50%").await, ["treasury"]);
+    assert_eq!(h.search("%").await, ["treasury"]);
+    assert_eq!(h.search("_").await, ["cold_store", "hot1"]);
+    assert!(h.search("\\").await.is_empty());
+    // 布尔值不参与匹配
+    assert!(h.search("true").await.is_empty());
+    assert_eq!(h.search("  ").await.len(), WALLETS.len());
+
+    // 搜索结果中带上描述和元数据
+    let res = h
+        .app
+        .get("/api/wallets?search=treasury")
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .await;
+    let listed: Vec<Value> = res.json();
+    assert_eq!(listed[0]["description"], "Main treasury, 50% of float");
+    assert!(listed[0].get("metadata").is_none());
+
+    // 与游标分页组合
+    let res = h
+        .app
+        .get("/api/wallets?search=_&limit=1")
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .await;
+    let cursor = res.headers().get("x-next-cursor").unwrap().to_str().unwrap().to_string();
+    assert_eq!(res.json::<Vec<Value>>()[0]["name"], "cold_store");
+    let rest: Vec<Value> = h
+        .app
+        .get(&format!("/api/wallets?search=_&limit=1&cursor={}", cursor))
+        .add_header("Authorization", format!("Bearer {}", OWNER_TOKEN))
+        .await
+        .json();
+    assert_eq!(rest.iter().map(|w| w["name"].clone()).collect::<Vec<_>>(), [json!("hot1")]);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_notes_round_trip_through_backup_and_restore() {
+    let h = build().await;
+    let metadata = json!({ "team": "treasury-ops", "tier": 2 });
+    h.patch_ok("treasury", json!({ "description": "cold reserve", "metadata": metadata })).await;
+
+    let res = h.app.get("/api/wallets/treasury/backup").add_header("X-API-KEY", API_KEY).await;
+    res.assert_status_ok();
+    let backup: Value = res.json();
+    assert_eq!(backup["description"], "cold reserve");
+    assert_eq!(backup["metadata"], metadata);
+    let mnemonic = base64::engine::general_purpose::STANDARD.decode(backup["ciphertext"].as_str().unwrap()).unwrap();
+    let mnemonic = String::from_utf8(mnemonic).unwrap();
+
+    let restore = |name: &str, metadata: Value| {
+        json!({
+            "name": name,
+            "seed_phrase": mnemonic,
+            "description": backup["description"],
+            "metadata": metadata,
+        })
+    };
+    let res = h
+        .app
+        .post("/api/wallets/restore")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&restore("treasury_copy", metadata.clone()))
+        .await;
+    res.assert_status_ok();
+    let restored: Value = res.json();
+    assert_eq!(restored["description"], "cold reserve");
+    assert_eq!(restored["metadata"], metadata);
+    let notes = h.storage.wallet_notes("treasury_copy").await.unwrap().unwrap();
+    assert_eq!(notes.description.as_deref(), Some("cold reserve"));
+    assert_eq!(Value::Object(notes.metadata), metadata);
+
+    // 备份里的元数据与 PATCH 同样校验；被拒绝时wallet不会被恢复
+    let res = h
+        .app
+        .post("/api/wallets/restore")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&restore("tampered", json!({ "key_material": "x" })))
+        .await;
+    res.assert_status_bad_request();
+    assert_eq!(code(&res), "SECRET_IN_WALLET_NOTES");
+    assert!(h.storage.wallet_notes("tampered").await.unwrap().is_none());
+
+    // 没有备注的wallet，备份中不出现这两个字段
+    let backup: Value = h.app.get("/api/wallets/cold_store/backup").add_header("X-API-KEY", API_KEY).await.json();
+    assert!(backup.get("description").is_none() && backup.get("metadata").is_none());
+}
@@ -0,0 +1,142 @@
+//! 钱包创建资金预检 (`?preflight=`) 与 `/funding_requirements` 集成测试
+//!
+//! 使用 ethers MockProvider 注入 gas oracle，不访问真实 RPC。
+
+mod util;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use ethers::providers::{MockProvider, Provider};
+use ethers::types::U256;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+use defi_hot_wallet::blockchain::gas_oracle::{GasOracle, ProviderGasOracle};
+
+const TOKEN: &str = "preflight-test-token";
+const ADDR: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+
+async fn build_app(oracle: Arc<dyn GasOracle>) -> (axum::Router, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server(dir.path(), util::memory_config(), None).await.with_gas_oracle(oracle);
+    util::sign_in(&server, "preflight@example.com", TOKEN).await;
+
+    (server.create_router().await, dir)
+}
+
+fn mocked_oracle(responses: &[U256]) -> Arc<dyn GasOracle> {
+    let (provider, mock) = Provider::mocked();
+    for r in responses {
+        mock.push(*r).unwrap();
+    }
+    Arc::new(ProviderGasOracle::<MockProvider>::new().with_provider("eth", provider))
+}
+
+async fn create_wallet(app: axum::Router, uri: &str, name: &str) -> (StatusCode, serde_json::Value) {
+    let body = serde_json::json!({ "name": name, "wallet_address": ADDR });
+    let req = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("Authorization", format!("Bearer {}", TOKEN))
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let res = app.oneshot(req).await.unwrap();
+    let status = res.status();
+    let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_create_wallet_with_preflight_returns_estimates() {
+    let oracle = mocked_oracle(&[U256::from(20_000_000_000u64), U256::zero()]);
+    let (app, _dir) = build_app(oracle).await;
+
+    let (status, json) = create_wallet(app, "/api/wallets?preflight=eth", "pf_ok").await;
+    assert!(status.is_success());
+
+    let pf = json["preflight"].as_array().expect("preflight array");
+    assert_eq!(pf.len(), 1);
+    assert_eq!(pf[0]["network"], "eth");
+    assert_eq!(pf[0]["address"], ADDR);
+    assert!(!pf[0]["gas_price_wei"].is_null());
+    assert!(!pf[0]["min_transfer_amount"].is_null());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_create_wallet_preflight_rpc_failure_still_creates() {
+    // 不推送响应：所有 RPC 调用失败
+    let oracle = mocked_oracle(&[]);
+    let (app, _dir) = build_app(oracle).await;
+
+    let (status, json) = create_wallet(app, "/api/wallets?preflight=eth", "pf_fail").await;
+    assert!(status.is_success());
+    assert_eq!(json["name"], "pf_fail");
+    let pf = &json["preflight"][0];
+    assert!(pf["balance"].is_null());
+    assert!(pf["gas_price_wei"].is_null());
+    assert!(pf["min_transfer_amount"].is_null());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_create_wallet_without_preflight_omits_field() {
+    let (app, _dir) = build_app(mocked_oracle(&[])).await;
+    let (status, json) = create_wallet(app, "/api/wallets", "pf_none").await;
+    assert!(status.is_success());
+    assert!(json.get("preflight").is_none());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_funding_requirements_matches_preflight() {
+    let gas = U256::from(15_000_000_000u64);
+    let oracle = mocked_oracle(&[gas, U256::zero(), gas, U256::zero()]);
+    let (app, _dir) = build_app(oracle).await;
+
+    let (_, created) = create_wallet(app.clone(), "/api/wallets?preflight=eth", "pf_match").await;
+
+    let req = Request::builder()
+        .method("GET")
+        .uri("/api/wallets/pf_match/funding_requirements?network=eth")
+        .header("Authorization", format!("Bearer {}", TOKEN))
+        .body(Body::empty())
+        .unwrap();
+    let res = app.oneshot(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let standalone: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+    assert_eq!(standalone, created["preflight"][0]);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_btc_preflight_reports_unsupported_network() {
+    let oracle = mocked_oracle(&[U256::from(20_000_000_000u64), U256::zero()]);
+    let (app, _dir) = build_app(oracle).await;
+
+    let (status, json) = create_wallet(app.clone(), "/api/wallets?preflight=eth,btc", "pf_btc").await;
+    assert!(status.is_success());
+    let pf = json["preflight"].as_array().expect("preflight array");
+    assert!(pf[0].get("error").is_none());
+    assert_eq!(pf[1]["network"], "btc");
+    assert_eq!(pf[1]["error"], "UNSUPPORTED_NETWORK");
+    assert!(pf[1]["address"].is_null());
+
+    let req = Request::builder()
+        .method("GET")
+        .uri("/api/wallets/pf_btc/funding_requirements?network=btc")
+        .header("Authorization", format!("Bearer {}", TOKEN))
+        .body(Body::empty())
+        .unwrap();
+    let res = app.oneshot(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(json["error"]["code"], "UNSUPPORTED_NETWORK");
+}
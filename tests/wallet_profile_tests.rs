@@ -0,0 +1,125 @@
+//! wallet行为画像：确认时增量更新，与 admin 重建结果一致
+
+use axum_test::TestServer;
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::security::SecretVec;
+use defi_hot_wallet::storage::{TransactionRecord, WalletStorage};
+
+const API_KEY: &str = "wallet-profile-test-key-0123456789";
+const RECIPIENTS: [&str; 3] = [
+    "0x1111111111111111111111111111111111111111",
+    "0x2222222222222222222222222222222222222222",
+    "0x3333333333333333333333333333333333333333",
+];
+
+fn record(i: usize, wallet_id: &str, to: &str, amount: &str, created_at: DateTime<Utc>) -> TransactionRecord {
+    TransactionRecord {
+        id: format!("profile-tx-{:03}", i),
+        wallet_id: wallet_id.to_string(),
+        tx_hash: format!("0x{:064x}", i + 1),
+        network: "eth".to_string(),
+        from_address: "0x1234567890123456789012345678901234567890".to_string(),
+        to_address: to.to_string(),
+        amount: amount.to_string(),
+        fee: "0.001".to_string(),
+        status: "pending".to_string(),
+        created_at,
+        confirmed_at: None,
+        integrity_hash: String::new(),
+        fee_actual: None,
+        block_number: None,
+    }
+}
+
+/// 新建wallet并写入 20 笔 0.05–0.15 ETH 的发送（第 7 笔failed），依次确认；返回确认笔数
+async fn seed_history(storage: &WalletStorage, wallet: &str) -> usize {
+    // 落库wallet写审计记录，其 MAC 需要 WALLET_ENC_KEY
+    std::env::set_var("WALLET_ENC_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    storage.store_wallet(wallet, b"blob", false).await.unwrap();
+    let wallet_id = storage.wallet_id(wallet).await.unwrap().unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap();
+    let mut confirmed = 0;
+    for i in 0..20 {
+        let amount = format!("{:.3}", 0.05 + (i * 37 % 11) as f64 * 0.01);
+        let hour = if i % 2 == 0 { 10 } else { 14 };
+        let created_at = start + chrono::Duration::days(i as i64 * 3) + chrono::Duration::hours(hour);
+        let tx = record(i, &wallet_id, RECIPIENTS[i % RECIPIENTS.len()], &amount, created_at);
+        storage.store_transaction(&tx).await.unwrap();
+        let status = if i == 7 { "failed" } else { "confirmed" };
+        storage.update_transaction_status(&tx.id, status, Some(created_at)).await.unwrap();
+        confirmed += usize::from(status == "confirmed");
+    }
+    confirmed
+}
+
+#[tokio::test]
+async fn test_incremental_profile_matches_rebuild() {
+    let storage = WalletStorage::new_with_url("sqlite::memory:").await.unwrap();
+    let confirmed = seed_history(&storage, "treasury").await;
+    // 重复确认不会重复计数
+    storage.update_transaction_status("profile-tx-000", "confirmed", None).await.unwrap();
+
+    let incremental = storage.wallet_profile("treasury", "eth").await.unwrap().unwrap();
+    assert_eq!(incremental.sample_count, confirmed as u64);
+    assert_eq!(incremental.distinct_recipients, 3);
+    assert_eq!(incremental.hour_histogram[10] + incremental.hour_histogram[14], confirmed as u64);
+    assert!(storage.is_known_recipient("treasury", "eth", RECIPIENTS[1]).await.unwrap());
+    assert!(!storage.is_known_recipient("treasury", "eth", "0x4444444444444444444444444444444444444444").await.unwrap());
+    assert!(storage.wallet_profile("treasury", "polygon").await.unwrap().is_none());
+
+    let report = storage.rebuild_wallet_profiles(None).await.unwrap();
+    assert_eq!((report.profiles, report.transactions), (1, confirmed));
+    let rebuilt = storage.wallet_profile("treasury", "eth").await.unwrap().unwrap();
+    assert_eq!(rebuilt, incremental);
+}
+
+#[tokio::test]
+async fn test_admin_rebuild_endpoint() {
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap();
+    let confirmed = seed_history(&server.storage, "ops").await;
+    let before = server.storage.wallet_profile("ops", "eth").await.unwrap().unwrap();
+    let storage = server.storage.clone();
+    let app = TestServer::new(server.create_router().await).unwrap();
+
+    app.post("/api/admin/wallet-profiles/rebuild").json(&json!({})).await.assert_status_unauthorized();
+
+    let body: Value = app
+        .post("/api/admin/wallet-profiles/rebuild")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "wallet_id": "ops" }))
+        .await
+        .json();
+    assert_eq!(body, json!({ "profiles": 1, "transactions": confirmed }));
+    assert_eq!(storage.wallet_profile("ops", "eth").await.unwrap().unwrap(), before);
+
+    let body: Value = app
+        .post("/api/admin/wallet-profiles/rebuild")
+        .add_header("X-API-KEY", API_KEY)
+        .json(&json!({ "wallet_id": "nobody" }))
+        .await
+        .json();
+    assert_eq!(body["profiles"], 0);
+    // 只重建指定wallet，其他wallet的画像保留
+    assert!(storage.wallet_profile("ops", "eth").await.unwrap().is_some());
+}
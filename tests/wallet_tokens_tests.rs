@@ -0,0 +1,264 @@
+//! wallet范围 token（`/api/wallets/:name/tokens`）集成测试
+
+mod util;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Eip1559TransactionRequest};
+use serde_json::{json, Value};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::storage::{hash_token, AuditLog, NewWalletToken, WalletCapability, AUTH_MODE_BEARER};
+
+const API_KEY: &str = "wallet-tokens-admin-key";
+const SESSION: &str = "wallet-tokens-session";
+const ADDRESS: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+
+struct Harness {
+    server: WalletServer,
+    app: TestServer,
+    user_id: String,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    let server = util::test_server(dir.path(), util::memory_config(), Some(API_KEY)).await;
+
+    let user_id = util::sign_in(&server, "integrations@example.com", SESSION).await;
+    for name in ["treasury", "payroll"] {
+        server.user_db.link_wallet(&user_id, name, ADDRESS, None).await.unwrap();
+    }
+
+    let app = TestServer::new(server.clone().create_router().await).unwrap();
+    Harness { server, app, user_id, _dir: dir }
+}
+
+/// owner 通过会话 token 签发，返回 `(token, token_id)`
+async fn issue(h: &Harness, wallet: &str, body: Value) -> (String, String) {
+    let res = h
+        .app
+        .post(&format!("/api/wallets/{}/tokens", wallet))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&body)
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    (body["token"].as_str().unwrap().to_string(), body["id"].as_str().unwrap().to_string())
+}
+
+fn used_token(log: &AuditLog, token_id: &str) -> bool {
+    log.action == "wallet_token.use" && log.details.as_deref().is_some_and(|d| d.contains(token_id))
+}
+
+/// 外部sign的 mainnet EIP-1559 transaction（发送前会校验 chain id）
+fn signed_tx() -> String {
+    let wallet = LocalWallet::from_bytes(&[0x07; 32]).unwrap().with_chain_id(1u64);
+    let tx: TypedTransaction = Eip1559TransactionRequest::new()
+        .to(RECIPIENT.parse::<Address>().unwrap())
+        .value(1u64)
+        .gas(21_000u64)
+        .max_fee_per_gas(20_000_000_000u64)
+        .max_priority_fee_per_gas(1_000_000_000u64)
+        .nonce(0u64)
+        .chain_id(1u64)
+        .into();
+    let signature = wallet.sign_transaction_sync(&tx).unwrap();
+    format!("0x{}", hex::encode(tx.rlp_signed(&signature)))
+}
+
+fn send_body(amount: &str) -> Value {
+    json!({ "to": RECIPIENT, "amount": amount, "network": "eth", "signed_tx": signed_tx() })
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_scoped_token_reads_own_wallet_only() {
+    let h = build().await;
+    let (token, token_id) = issue(&h, "treasury", json!({ "capabilities": ["read_balance"] })).await;
+    assert!(token.starts_with("wtk_"));
+
+    let res = h
+        .app
+        .get("/api/wallets/treasury/balance?network=eth")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    res.assert_status_ok();
+
+    // 同一 owner 的另一个wallet也不行
+    let res = h
+        .app
+        .get("/api/wallets/payroll/balance?network=eth")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(res.json::<Value>()["error"]["code"], "WALLET_SCOPE_MISMATCH");
+
+    // 没有 send capability
+    let res = h
+        .app
+        .post("/api/wallets/treasury/send")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&send_body("0.1"))
+        .await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(res.json::<Value>()["error"]["code"], "TOKEN_CAPABILITY_DENIED");
+
+    // token 不能管理 token
+    let res = h
+        .app
+        .get("/api/wallets/treasury/tokens")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    res.assert_status(StatusCode::FORBIDDEN);
+
+    // 列表不含明文或哈希
+    let res = h
+        .app
+        .get("/api/wallets/treasury/tokens")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await;
+    res.assert_status_ok();
+    let listed: Value = res.json();
+    assert_eq!(listed["tokens"][0]["id"], token_id.as_str());
+    assert_eq!(listed["tokens"][0]["capabilities"], json!(["read_balance"]));
+    assert!(!listed.to_string().contains(&token));
+    assert!(!listed.to_string().contains(&hash_token(&token)));
+
+    let logs = h.server.storage.get_audit_logs(Some("treasury")).await.unwrap();
+    assert!(logs.iter().any(|log| used_token(log, &token_id)));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_send_respects_token_amount_cap() {
+    let h = build().await;
+    let (token, token_id) =
+        issue(&h, "treasury", json!({ "capabilities": ["send"], "amount_cap": "0.5" })).await;
+
+    let res = h
+        .app
+        .post("/api/wallets/treasury/send")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&send_body("0.5"))
+        .await;
+    res.assert_status_ok();
+
+    let res = h
+        .app
+        .post("/api/wallets/treasury/send")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&send_body("0.500000000000000001"))
+        .await;
+    res.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(res.json::<Value>()["error"]["code"], "TOKEN_AMOUNT_CAP_EXCEEDED");
+
+    // owner 自己的会话不受 token 上限约束
+    let res = h
+        .app
+        .post("/api/wallets/treasury/send")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&send_body("2"))
+        .await;
+    res.assert_status_ok();
+
+    let logs = h.server.storage.get_audit_logs(Some("treasury")).await.unwrap();
+    assert_eq!(logs.iter().filter(|log| used_token(log, &token_id)).count(), 2);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_expired_token_is_rejected() {
+    let h = build().await;
+    let (wallet_id, _) =
+        h.server.user_db.find_user_wallet(&h.user_id, "treasury").await.unwrap().unwrap();
+    let token = "wtk_expired-integration-token";
+    h.server
+        .storage
+        .insert_wallet_token(&NewWalletToken {
+            id: "expired-token",
+            token_hash: &hash_token(token),
+            wallet_id,
+            wallet_name: "treasury",
+            owner_user_id: &h.user_id,
+            capabilities: WalletCapability::ReadBalance.bit(),
+            amount_cap: None,
+            expires_at: chrono::Utc::now().timestamp() - 60,
+            created_by: &h.user_id,
+            auth_mode: AUTH_MODE_BEARER,
+            signing_secret: None,
+        })
+        .await
+        .unwrap();
+
+    let res = h
+        .app
+        .get("/api/wallets/treasury/balance?network=eth")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    res.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(res.json::<Value>()["error"]["code"], "TOKEN_EXPIRED");
+
+    // 有效期越界在签发时拒绝
+    let res = h
+        .app
+        .post("/api/wallets/treasury/tokens")
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .json(&json!({ "capabilities": ["read_balance"], "expires_in_secs": 0 }))
+        .await;
+    res.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_revocation_takes_effect_immediately() {
+    let h = build().await;
+
+    // admin 签发需要指明 owner
+    let res = h
+        .app
+        .post("/api/wallets/treasury/tokens")
+        .add_header("Authorization", API_KEY)
+        .json(&json!({ "capabilities": ["read_balance"] }))
+        .await;
+    res.assert_status(StatusCode::BAD_REQUEST);
+    let res = h
+        .app
+        .post("/api/wallets/treasury/tokens")
+        .add_header("Authorization", API_KEY)
+        .json(&json!({ "capabilities": ["read_balance"], "owner_user_id": h.user_id }))
+        .await;
+    res.assert_status_ok();
+    let body: Value = res.json();
+    assert_eq!(body["created_by"], "admin");
+    let token = body["token"].as_str().unwrap().to_string();
+    let token_id = body["id"].as_str().unwrap().to_string();
+
+    let balance = || {
+        h.app
+            .get("/api/wallets/treasury/balance?network=eth")
+            .add_header("Authorization", format!("Bearer {}", token))
+    };
+    balance().await.assert_status_ok();
+
+    let res = h
+        .app
+        .delete(&format!("/api/wallets/treasury/tokens/{}", token_id))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await;
+    res.assert_status_ok();
+
+    let res = balance().await;
+    res.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(res.json::<Value>()["error"]["code"], "INVALID_TOKEN");
+
+    // 重复吊销
+    h.app
+        .delete(&format!("/api/wallets/treasury/tokens/{}", token_id))
+        .add_header("Authorization", format!("Bearer {}", SESSION))
+        .await
+        .assert_status_not_found();
+}
@@ -40,6 +40,7 @@ async fn create_test_manager() -> WalletManager {
             database_url: db_url,
             max_connections: Some(5),
             connection_timeout_seconds: Some(10),
+            read_database_url: None,
         },
         blockchain: BlockchainConfig {
             networks: HashMap::new(),
@@ -48,6 +49,27 @@ async fn create_test_manager() -> WalletManager {
         multi_sig_threshold: 2,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
     
     WalletManager::new(&config).await.expect("Failed to create WalletManager")
@@ -61,7 +83,7 @@ async fn create_test_manager() -> WalletManager {
 async fn test_create_wallet_success() {
     let manager = create_test_manager().await;
     
-    let result = manager.create_wallet("test_wallet_success", false).await;
+    let result = manager.create_wallet("test_wallet_success", "test_password", false).await;
     
     assert!(result.is_ok(), "创建钱包应该成功");
     
@@ -75,13 +97,13 @@ async fn test_create_wallet_success() {
 async fn test_create_wallet_quantum_safe() {
     let manager = create_test_manager().await;
     
-    let result = manager.create_wallet("quantum_wallet", true).await;
+    let result = manager.create_wallet("quantum_wallet", "test_password", true).await;
     
     assert!(result.is_ok(), "创建量子安全钱包应该成功");
     
     let wallets = manager.list_wallets().await.unwrap();
     assert_eq!(wallets.len(), 1);
-    assert_eq!(wallets[0].quantum_safe, true, "应该是量子安全钱包");
+    assert!(wallets[0].quantum_safe, "应该是量子安全钱包");
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 20)]
@@ -89,10 +111,10 @@ async fn test_create_wallet_duplicate_name_fails() {
     let manager = create_test_manager().await;
     
     // 第一次创建应该成功
-    manager.create_wallet("duplicate_wallet", false).await.unwrap();
+    manager.create_wallet("duplicate_wallet", "test_password", false).await.unwrap();
     
     // 第二次创建相同名称应该失败
-    let result = manager.create_wallet("duplicate_wallet", false).await;
+    let result = manager.create_wallet("duplicate_wallet", "test_password", false).await;
     
     assert!(result.is_err(), "重复名称应该失败");
     
@@ -105,7 +127,7 @@ async fn test_create_wallet_duplicate_name_fails() {
 async fn test_create_wallet_empty_name() {
     let manager = create_test_manager().await;
     
-    let result = manager.create_wallet("", false).await;
+    let result = manager.create_wallet("", "test_password", false).await;
     
     // TODO: 当前实现允许空名称，应该添加验证逻辑拒绝
     // 暂时记录实际行为
@@ -116,7 +138,7 @@ async fn test_create_wallet_empty_name() {
 async fn test_create_wallet_with_dash() {
     let manager = create_test_manager().await;
     
-    let result = manager.create_wallet("wallet-name", false).await;
+    let result = manager.create_wallet("wallet-name", "test_password", false).await;
     
     // TODO: 当前实现允许连字符，可能需要根据业务需求决定是否限制
     let _ = result; // 不强制要求失败
@@ -126,7 +148,7 @@ async fn test_create_wallet_with_dash() {
 async fn test_create_wallet_with_space() {
     let manager = create_test_manager().await;
     
-    let result = manager.create_wallet("wallet name", false).await;
+    let result = manager.create_wallet("wallet name", "test_password", false).await;
     
     // TODO: 当前实现允许空格，可能需要根据业务需求决定是否限制
     let _ = result; // 不强制要求失败
@@ -145,7 +167,7 @@ async fn test_create_wallet_with_special_chars() {
     ];
     
     for name in test_names {
-        let result = manager.create_wallet(name, false).await;
+        let result = manager.create_wallet(name, "test_password", false).await;
         // TODO: 当前实现允许某些特殊字符，可能需要根据业务需求决定是否限制
         let _ = result; // 不强制要求失败
     }
@@ -157,12 +179,12 @@ async fn test_create_wallet_very_long_name() {
     
     // 创建一个正常长度的名称 (应该成功)
     let normal_name = "a".repeat(50);
-    let result = manager.create_wallet(&normal_name, false).await;
+    let result = manager.create_wallet(&normal_name, "test_password", false).await;
     assert!(result.is_ok(), "50字符名称应该成功");
     
     // 创建一个超长名称 (可能失败，取决于实现)
     let long_name = "a".repeat(256);
-    let _result = manager.create_wallet(&long_name, false).await;
+    let _result = manager.create_wallet(&long_name, "test_password", false).await;
     // 注意：这个测试只是执行，不强制要求失败，因为实现可能允许长名称
 }
 
@@ -175,7 +197,7 @@ async fn test_delete_wallet_success() {
     let manager = create_test_manager().await;
     
     // 先创建钱包
-    manager.create_wallet("to_delete", false).await.unwrap();
+    manager.create_wallet("to_delete", "test_password", false).await.unwrap();
     
     // 验证钱包存在
     let wallets = manager.list_wallets().await.unwrap();
@@ -204,7 +226,7 @@ async fn test_delete_wallet_twice_fails() {
     let manager = create_test_manager().await;
     
     // 创建并删除钱包
-    manager.create_wallet("delete_twice", false).await.unwrap();
+    manager.create_wallet("delete_twice", "test_password", false).await.unwrap();
     manager.delete_wallet("delete_twice").await.unwrap();
     
     // 第二次删除应该失败
@@ -229,7 +251,7 @@ async fn test_list_wallets_empty() {
 async fn test_list_wallets_single() {
     let manager = create_test_manager().await;
     
-    manager.create_wallet("single_wallet", false).await.unwrap();
+    manager.create_wallet("single_wallet", "test_password", false).await.unwrap();
     
     let wallets = manager.list_wallets().await.unwrap();
     
@@ -242,10 +264,10 @@ async fn test_list_wallets_multiple() {
     let manager = create_test_manager().await;
     
     // 创建多个钱包
-    manager.create_wallet("wallet_1", false).await.unwrap();
-    manager.create_wallet("wallet_2", false).await.unwrap();
-    manager.create_wallet("wallet_3", false).await.unwrap();
-    manager.create_wallet("wallet_4", true).await.unwrap(); // 量子安全
+    manager.create_wallet("wallet_1", "test_password", false).await.unwrap();
+    manager.create_wallet("wallet_2", "test_password", false).await.unwrap();
+    manager.create_wallet("wallet_3", "test_password", false).await.unwrap();
+    manager.create_wallet("wallet_4", "test_password", true).await.unwrap(); // 量子安全
     
     let wallets = manager.list_wallets().await.unwrap();
     
@@ -261,9 +283,9 @@ async fn test_list_wallets_after_delete() {
     let manager = create_test_manager().await;
     
     // 创建3个钱包
-    manager.create_wallet("wallet_a", false).await.unwrap();
-    manager.create_wallet("wallet_b", false).await.unwrap();
-    manager.create_wallet("wallet_c", false).await.unwrap();
+    manager.create_wallet("wallet_a", "test_password", false).await.unwrap();
+    manager.create_wallet("wallet_b", "test_password", false).await.unwrap();
+    manager.create_wallet("wallet_c", "test_password", false).await.unwrap();
     
     // 删除中间的钱包
     manager.delete_wallet("wallet_b").await.unwrap();
@@ -292,7 +314,7 @@ async fn test_concurrent_wallet_creation() {
     for i in 0..10 {
         let mgr = manager.clone();
         let handle = tokio::spawn(async move {
-            mgr.create_wallet(&format!("concurrent_wallet_{}", i), false).await
+            mgr.create_wallet(&format!("concurrent_wallet_{}", i), "test_password", false).await
         });
         handles.push(handle);
     }
@@ -318,7 +340,7 @@ async fn test_concurrent_create_and_delete() {
     
     // 先创建5个钱包
     for i in 0..5 {
-        manager.create_wallet(&format!("wallet_{}", i), false).await.unwrap();
+        manager.create_wallet(&format!("wallet_{}", i), "test_password", false).await.unwrap();
     }
     
     let mut handles = vec![];
@@ -327,7 +349,7 @@ async fn test_concurrent_create_and_delete() {
     for i in 5..10 {
         let mgr = manager.clone();
         let handle = tokio::spawn(async move {
-            mgr.create_wallet(&format!("wallet_{}", i), false).await
+            mgr.create_wallet(&format!("wallet_{}", i), "test_password", false).await
         });
         handles.push(handle);
     }
@@ -351,7 +373,7 @@ async fn test_concurrent_list_operations() {
     
     // 创建一些钱包
     for i in 0..5 {
-        manager.create_wallet(&format!("list_wallet_{}", i), false).await.unwrap();
+        manager.create_wallet(&format!("list_wallet_{}", i), "test_password", false).await.unwrap();
     }
     
     let mut handles = vec![];
@@ -406,6 +428,7 @@ async fn test_wallet_persistence_across_managers() {
                 database_url: db_url.clone(),
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             blockchain: BlockchainConfig {
                 networks: HashMap::new(),
@@ -414,10 +437,31 @@ async fn test_wallet_persistence_across_managers() {
             multi_sig_threshold: 2,
             derivation: Default::default(),
             security: SecurityConfig::default(),
+            bridge_backend: Default::default(),
+            balance_snapshots: Default::default(),
+            backups: Default::default(),
+            relay: Default::default(),
+            cluster: Default::default(),
+            account_abstraction: Default::default(),
+            fee_tracking: Default::default(),
+            approvals: Default::default(),
+            pricing: Default::default(),
+            jobs: Default::default(),
+            wal: Default::default(),
+            database_queries: Default::default(),
+            recipient_guard: Default::default(),
+            reserve_reports: Default::default(),
+            admission: Default::default(),
+            backfill: Default::default(),
+            feature_flags: Default::default(),
+            tokens: Default::default(),
+            timelocks: Default::default(),
+            bridge_poller: Default::default(),
+            anomaly_detection: Default::default(),
         };
         
         let manager = WalletManager::new(&config).await.unwrap();
-        manager.create_wallet("persistent_wallet", false).await.unwrap();
+        manager.create_wallet("persistent_wallet", "test_password", false).await.unwrap();
     }
     
     // 第二个manager应该能看到钱包
@@ -427,6 +471,7 @@ async fn test_wallet_persistence_across_managers() {
                 database_url: db_url,
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             blockchain: BlockchainConfig {
                 networks: HashMap::new(),
@@ -435,6 +480,27 @@ async fn test_wallet_persistence_across_managers() {
             multi_sig_threshold: 2,
             derivation: Default::default(),
             security: SecurityConfig::default(),
+            bridge_backend: Default::default(),
+            balance_snapshots: Default::default(),
+            backups: Default::default(),
+            relay: Default::default(),
+            cluster: Default::default(),
+            account_abstraction: Default::default(),
+            fee_tracking: Default::default(),
+            approvals: Default::default(),
+            pricing: Default::default(),
+            jobs: Default::default(),
+            wal: Default::default(),
+            database_queries: Default::default(),
+            recipient_guard: Default::default(),
+            reserve_reports: Default::default(),
+            admission: Default::default(),
+            backfill: Default::default(),
+            feature_flags: Default::default(),
+            tokens: Default::default(),
+            timelocks: Default::default(),
+            bridge_poller: Default::default(),
+            anomaly_detection: Default::default(),
         };
         
         let manager = WalletManager::new(&config).await.unwrap();
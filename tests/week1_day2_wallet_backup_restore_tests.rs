@@ -39,6 +39,7 @@ async fn create_test_manager() -> WalletManager {
             database_url: db_url,
             max_connections: Some(5),
             connection_timeout_seconds: Some(10),
+            read_database_url: None,
         },
         blockchain: BlockchainConfig {
             networks: HashMap::new(),
@@ -47,6 +48,27 @@ async fn create_test_manager() -> WalletManager {
         multi_sig_threshold: 2,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
     
     WalletManager::new(&config).await.expect("Failed to create WalletManager")
@@ -62,7 +84,7 @@ async fn test_backup_wallet_success() {
     let manager = create_test_manager().await;
     
     // 创建钱包
-    manager.create_wallet("backup_test", false).await.unwrap();
+    manager.create_wallet("backup_test", "test_password", false).await.unwrap();
     
     // 备份钱包
     let backup_result = manager.backup_wallet("backup_test").await;
@@ -98,7 +120,7 @@ async fn test_backup_quantum_safe_wallet() {
     let manager = create_test_manager().await;
     
     // 创建量子安全钱包
-    manager.create_wallet("quantum_backup", true).await.unwrap();
+    manager.create_wallet("quantum_backup", "test_password", true).await.unwrap();
     
     // 备份量子安全钱包
     let backup_result = manager.backup_wallet("quantum_backup").await;
@@ -124,7 +146,7 @@ async fn test_backup_multiple_wallets() {
     
     // 创建多个钱包
     for i in 0..5 {
-        manager.create_wallet(&format!("wallet_{}", i), false).await.unwrap();
+        manager.create_wallet(&format!("wallet_{}", i), "test_password", false).await.unwrap();
     }
     
     // 备份所有钱包
@@ -235,7 +257,7 @@ async fn test_restore_wallet_quantum_safe() {
     // 验证钱包是量子安全的
     let wallets = manager.list_wallets().await.unwrap();
     assert_eq!(wallets.len(), 1);
-    assert_eq!(wallets[0].quantum_safe, true, "恢复的钱包应该是量子安全的");
+    assert!(wallets[0].quantum_safe, "恢复的钱包应该是量子安全的");
 }
 
 // ============================================================================
@@ -248,7 +270,7 @@ async fn test_backup_and_verify_data() {
     let manager = create_test_manager().await;
     
     // 创建钱包
-    manager.create_wallet("backup_verify", false).await.unwrap();
+    manager.create_wallet("backup_verify", "test_password", false).await.unwrap();
     
     // 备份
     let backup_result = manager.backup_wallet("backup_verify").await;
@@ -326,6 +348,7 @@ async fn test_restore_across_managers() {
                 database_url: db_url.clone(),
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             blockchain: BlockchainConfig {
                 networks: HashMap::new(),
@@ -334,6 +357,27 @@ async fn test_restore_across_managers() {
             multi_sig_threshold: 2,
             derivation: Default::default(),
             security: SecurityConfig::default(),
+            bridge_backend: Default::default(),
+            balance_snapshots: Default::default(),
+            backups: Default::default(),
+            relay: Default::default(),
+            cluster: Default::default(),
+            account_abstraction: Default::default(),
+            fee_tracking: Default::default(),
+            approvals: Default::default(),
+            pricing: Default::default(),
+            jobs: Default::default(),
+            wal: Default::default(),
+            database_queries: Default::default(),
+            recipient_guard: Default::default(),
+            reserve_reports: Default::default(),
+            admission: Default::default(),
+            backfill: Default::default(),
+            feature_flags: Default::default(),
+            tokens: Default::default(),
+            timelocks: Default::default(),
+            bridge_poller: Default::default(),
+            anomaly_detection: Default::default(),
         };
         
         let manager1 = WalletManager::new(&config).await.unwrap();
@@ -347,6 +391,7 @@ async fn test_restore_across_managers() {
                 database_url: db_url,
                 max_connections: Some(5),
                 connection_timeout_seconds: Some(10),
+                read_database_url: None,
             },
             blockchain: BlockchainConfig {
                 networks: HashMap::new(),
@@ -355,6 +400,27 @@ async fn test_restore_across_managers() {
             multi_sig_threshold: 2,
             derivation: Default::default(),
             security: SecurityConfig::default(),
+            bridge_backend: Default::default(),
+            balance_snapshots: Default::default(),
+            backups: Default::default(),
+            relay: Default::default(),
+            cluster: Default::default(),
+            account_abstraction: Default::default(),
+            fee_tracking: Default::default(),
+            approvals: Default::default(),
+            pricing: Default::default(),
+            jobs: Default::default(),
+            wal: Default::default(),
+            database_queries: Default::default(),
+            recipient_guard: Default::default(),
+            reserve_reports: Default::default(),
+            admission: Default::default(),
+            backfill: Default::default(),
+            feature_flags: Default::default(),
+            tokens: Default::default(),
+            timelocks: Default::default(),
+            bridge_poller: Default::default(),
+            anomaly_detection: Default::default(),
         };
         
         let manager2 = WalletManager::new(&config).await.unwrap();
@@ -376,7 +442,7 @@ async fn test_concurrent_backup_operations() {
     
     // 创建多个钱包
     for i in 0..10 {
-        manager.create_wallet(&format!("backup_concurrent_{}", i), false).await.unwrap();
+        manager.create_wallet(&format!("backup_concurrent_{}", i), "test_password", false).await.unwrap();
     }
     
     let mut handles = vec![];
@@ -543,7 +609,7 @@ async fn test_multiple_backup_operations_same_wallet() {
     let manager = create_test_manager().await;
     
     // 创建钱包
-    manager.create_wallet("multi_backup", false).await.unwrap();
+    manager.create_wallet("multi_backup", "test_password", false).await.unwrap();
     
     // 多次备份同一个钱包
     let mut backups = Vec::new();
@@ -0,0 +1,215 @@
+//! `GET /api/ws`：订阅transaction状态推送（tokio-tungstenite 客户端，MockProvider 节点），
+//! 包括认证方式、pending → confirmed 推送，以及连接关闭后订阅被清理
+
+use ethers::providers::{MockProvider, Provider};
+use ethers::types::{Address, Transaction, TransactionReceipt, U256, U64};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+use defi_hot_wallet::api::server::WalletServer;
+use defi_hot_wallet::blockchain::client_registry::ClientRegistry;
+use defi_hot_wallet::blockchain::ethereum::EthereumClient;
+use defi_hot_wallet::blockchain::tx_status_hub::TxStatusHub;
+use defi_hot_wallet::core::config::{StorageConfig, WalletConfig};
+use defi_hot_wallet::security::SecretVec;
+
+const API_KEY: &str = "ws-status-admin-key";
+const HASH: &str = "0x3333333333333333333333333333333333333333333333333333333333333333";
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+struct Harness {
+    addr: SocketAddr,
+    hub: Arc<TxStatusHub>,
+    mock: MockProvider,
+    _dir: tempfile::TempDir,
+}
+
+async fn build() -> Harness {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("USERS_DATABASE_URL", format!("sqlite://{}", dir.path().join("users.db").display()));
+    let config = WalletConfig {
+        storage: StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: Some(5),
+            connection_timeout_seconds: Some(10),
+            read_database_url: None,
+        },
+        ..Default::default()
+    };
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    let client = EthereumClient::new_with_provider_and_chain(provider, "eth", 1);
+    let server = WalletServer::new_for_test(
+        "127.0.0.1".to_string(),
+        0,
+        config,
+        Some(SecretVec::new(API_KEY.as_bytes().to_vec())),
+        None,
+    )
+    .await
+    .unwrap()
+    .with_chain_clients(ClientRegistry::new().with_client("eth", Arc::new(client)))
+    .with_tx_status_hub(TxStatusHub::new(Duration::from_millis(20), Duration::from_millis(100)));
+    let hub = server.tx_status.clone();
+
+    // WebSocket 需要真实的 TCP 连接
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = server.create_router().await;
+    tokio::spawn(async move { axum::serve(listener, router.into_make_service()).await.unwrap() });
+    Harness { addr, hub, mock, _dir: dir }
+}
+
+impl Harness {
+    async fn connect(&self, query: &str) -> Result<Socket, WsError> {
+        connect_async(format!("ws://{}/api/ws{}", self.addr, query)).await.map(|(socket, _)| socket)
+    }
+
+    /// MockProvider 后进先出：第一次轮询 pending（无 receipt、有transaction），第二次 confirmed
+    fn script_pending_then_confirmed(&self) {
+        let receipt = TransactionReceipt {
+            transaction_hash: HASH.parse().unwrap(),
+            block_number: Some(U64::from(100)),
+            status: Some(U64::from(1)),
+            gas_used: Some(U256::from(21_000u64)),
+            ..Default::default()
+        };
+        let transaction = Transaction {
+            hash: HASH.parse().unwrap(),
+            from: Address::repeat_byte(0x11),
+            to: Some(Address::repeat_byte(0x42)),
+            value: U256::exp10(17),
+            ..Default::default()
+        };
+        self.mock.push(receipt).unwrap();
+        self.mock.push(transaction).unwrap();
+        self.mock.push(Value::Null).unwrap();
+    }
+
+    /// 订阅数在超时前降到 `expected`
+    async fn wait_active(&self, expected: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while self.hub.active() != expected {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("expected {} subscriptions, found {}", expected, self.hub.active()));
+    }
+}
+
+async fn send(socket: &mut Socket, message: Value) {
+    socket.send(Message::Text(message.to_string())).await.unwrap();
+}
+
+/// 下一条文本消息；连接关闭时返回 `None`
+async fn next(socket: &mut Socket) -> Option<Value> {
+    let read = async {
+        while let Some(message) = socket.next().await {
+            match message {
+                Ok(Message::Text(text)) => return Some(serde_json::from_str(&text).unwrap()),
+                Ok(Message::Close(_)) | Err(_) => return None,
+                Ok(_) => continue,
+            }
+        }
+        None
+    };
+    tokio::time::timeout(Duration::from_secs(5), read).await.expect("no ws message within 5s")
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_pushes_pending_then_confirmed() {
+    let h = build().await;
+    h.script_pending_then_confirmed();
+    let mut socket = h.connect(&format!("?api_key={}", API_KEY)).await.unwrap();
+
+    send(&mut socket, json!({ "subscribe": { "wallet": "alice", "tx_hash": HASH } })).await;
+    let subscribed = next(&mut socket).await.unwrap();
+    assert_eq!(subscribed["type"], "subscribed");
+    assert_eq!(subscribed["network"], "eth");
+    assert_eq!(subscribed["tx_hash"], HASH);
+
+    let pending = next(&mut socket).await.unwrap();
+    assert_eq!(pending["type"], "status");
+    assert_eq!(pending["wallet"], "alice");
+    assert_eq!(pending["status"], "pending");
+    assert!(pending["previous"].is_null());
+
+    let confirmed = next(&mut socket).await.unwrap();
+    assert_eq!(confirmed["status"], "confirmed");
+    assert_eq!(confirmed["previous"], "pending");
+    // 终态后不再轮询
+    h.wait_active(0).await;
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_wrong_api_key_is_refused_before_upgrade() {
+    let h = build().await;
+    match h.connect("?api_key=wrong").await {
+        Err(WsError::Http(response)) => assert_eq!(response.status(), 401),
+        other => panic!("expected 401, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_first_message_authentication() {
+    let h = build().await;
+
+    // 未认证就订阅：报错并关闭连接
+    let mut socket = h.connect("").await.unwrap();
+    send(&mut socket, json!({ "subscribe": { "wallet": "alice", "tx_hash": HASH } })).await;
+    let error = next(&mut socket).await.unwrap();
+    assert_eq!(error["type"], "error");
+    assert_eq!(error["code"], "AUTH_FAILED");
+    assert!(next(&mut socket).await.is_none());
+    assert_eq!(h.hub.active(), 0);
+
+    let mut socket = h.connect("").await.unwrap();
+    send(&mut socket, json!({ "auth": { "api_key": API_KEY } })).await;
+    assert_eq!(next(&mut socket).await.unwrap()["type"], "authenticated");
+
+    // 参数按 HTTP 接口的规则validate
+    send(&mut socket, json!({ "subscribe": { "wallet": "alice", "tx_hash": "0x1234" } })).await;
+    assert_eq!(next(&mut socket).await.unwrap()["code"], "INVALID_TX_HASH");
+    send(&mut socket, json!({ "subscribe": { "wallet": "alice", "tx_hash": HASH, "network": "btc" } })).await;
+    assert_eq!(next(&mut socket).await.unwrap()["code"], "UNSUPPORTED_NETWORK");
+    send(&mut socket, json!({ "hello": true })).await;
+    assert_eq!(next(&mut socket).await.unwrap()["code"], "INVALID_REQUEST");
+
+    send(&mut socket, json!({ "subscribe": { "wallet": "alice", "tx_hash": HASH } })).await;
+    assert_eq!(next(&mut socket).await.unwrap()["type"], "subscribed");
+    send(&mut socket, json!({ "unsubscribe": { "wallet": "alice", "tx_hash": HASH } })).await;
+    assert_eq!(next(&mut socket).await.unwrap()["type"], "unsubscribed");
+    h.wait_active(0).await;
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn test_closing_the_socket_drops_its_subscriptions() {
+    let h = build().await;
+    // 节点没有答复：状态一直未知，订阅保持
+    let mut first = h.connect(&format!("?api_key={}", API_KEY)).await.unwrap();
+    let mut second = h.connect(&format!("?api_key={}", API_KEY)).await.unwrap();
+    for socket in [&mut first, &mut second] {
+        send(socket, json!({ "subscribe": { "wallet": "alice", "tx_hash": HASH } })).await;
+        assert_eq!(next(socket).await.unwrap()["type"], "subscribed");
+    }
+    assert_eq!(h.hub.active(), 1);
+
+    // 仍有订阅者时保留
+    first.close(None).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(h.hub.active(), 1);
+
+    drop(second);
+    h.wait_active(0).await;
+}
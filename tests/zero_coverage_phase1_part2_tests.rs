@@ -57,6 +57,10 @@ fn test_bridge_transaction_struct() {
         updated_at: now,
         fee_amount: Some("0.5".to_string()),
         estimated_completion_time: None,
+        amount_minimal_source: None,
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
     };
     
     assert_eq!(tx.id, "tx123");
@@ -88,6 +92,10 @@ fn test_bridge_transaction_debug() {
         updated_at: Utc::now(),
         fee_amount: None,
         estimated_completion_time: None,
+        amount_minimal_source: None,
+        amount_minimal_dest: None,
+        token_address_source: None,
+        token_address_dest: None,
     };
     
     let debug_str = format!("{:?}", tx);
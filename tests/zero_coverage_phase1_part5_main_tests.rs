@@ -25,6 +25,7 @@ fn test_clap_args_structure() {
             database_url: "sqlite::memory:".to_string(),
             max_connections: Some(10),
             connection_timeout_seconds: Some(30),
+            read_database_url: None,
         },
         blockchain: BlockchainConfig {
             networks: HashMap::new(),
@@ -33,6 +34,27 @@ fn test_clap_args_structure() {
         multi_sig_threshold: 2,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
     
     assert_eq!(config.storage.max_connections, Some(10));
@@ -135,6 +157,10 @@ fn test_create_default_blockchain_config_structure() {
         name: "Ethereum Mainnet".to_string(),
         rpc_url: "https://eth.llamarpc.com".to_string(),
         chain_id: 1,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     // Ethereum Sepolia Testnet
@@ -142,6 +168,10 @@ fn test_create_default_blockchain_config_structure() {
         name: "Ethereum Sepolia".to_string(),
         rpc_url: "https://sepolia.drpc.org".to_string(),
         chain_id: 11155111,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     // Polygon
@@ -149,6 +179,10 @@ fn test_create_default_blockchain_config_structure() {
         name: "Polygon Mainnet".to_string(),
         rpc_url: "https://polygon-rpc.com".to_string(),
         chain_id: 137,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     // BSC
@@ -156,6 +190,10 @@ fn test_create_default_blockchain_config_structure() {
         name: "BSC Mainnet".to_string(),
         rpc_url: "https://bsc-dataseed.binance.org".to_string(),
         chain_id: 56,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     // BSC Testnet
@@ -163,6 +201,10 @@ fn test_create_default_blockchain_config_structure() {
         name: "BSC Testnet".to_string(),
         rpc_url: "https://data-seed-prebsc-1-s1.binance.org:8545".to_string(),
         chain_id: 97,
+        explorer_url_template: None,
+        rate_limit: None,
+        fallback_endpoints: Vec::new(),
+        pending_expiry_seconds: None,
     });
     
     let config = BlockchainConfig {
@@ -327,6 +369,7 @@ fn test_wallet_config_default_values() {
             database_url: "sqlite://./wallets.db".to_string(),
             max_connections: Some(10),
             connection_timeout_seconds: Some(30),
+            read_database_url: None,
         },
         blockchain: BlockchainConfig {
             networks: HashMap::new(),
@@ -335,6 +378,27 @@ fn test_wallet_config_default_values() {
         multi_sig_threshold: 2,
         derivation: Default::default(),
         security: SecurityConfig::default(),
+        bridge_backend: Default::default(),
+        balance_snapshots: Default::default(),
+        backups: Default::default(),
+        relay: Default::default(),
+        cluster: Default::default(),
+        account_abstraction: Default::default(),
+        fee_tracking: Default::default(),
+        approvals: Default::default(),
+        pricing: Default::default(),
+        jobs: Default::default(),
+        wal: Default::default(),
+        database_queries: Default::default(),
+        recipient_guard: Default::default(),
+        reserve_reports: Default::default(),
+        admission: Default::default(),
+        backfill: Default::default(),
+        feature_flags: Default::default(),
+        tokens: Default::default(),
+        timelocks: Default::default(),
+        bridge_poller: Default::default(),
+        anomaly_detection: Default::default(),
     };
     
     assert_eq!(wallet_config.storage.max_connections, Some(10));